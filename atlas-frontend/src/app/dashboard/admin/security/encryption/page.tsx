@@ -22,14 +22,17 @@ import {
   CheckCircle2,
   RotateCw,
   History,
-  Shield
+  Shield,
+  RefreshCw
 } from 'lucide-react';
-import type { EncryptionStatus } from '@/types/admin-security';
+import type { EncryptionStatus, ReencryptionStatus } from '@/types/admin-security';
 
 export default function EncryptionKeyPage() {
   const [loading, setLoading] = useState(true);
   const [error, setError] = useState<string | null>(null);
   const [data, setData] = useState<EncryptionStatus | null>(null);
+  const [reencryption, setReencryption] = useState<ReencryptionStatus | null>(null);
+  const [startingReencryption, setStartingReencryption] = useState(false);
 
   // Rotation dialog
   const [rotationDialog, setRotationDialog] = useState(false);
@@ -44,8 +47,12 @@ export default function EncryptionKeyPage() {
     try {
       setLoading(true);
       setError(null);
-      const result = await AdminSecurityService.getEncryptionStatus();
+      const [result, progress] = await Promise.all([
+        AdminSecurityService.getEncryptionStatus(),
+        AdminSecurityService.getReencryptionStatus(),
+      ]);
       setData(result);
+      setReencryption(progress);
     } catch (err) {
       const message = err instanceof Error ? err.message : 'Failed to load encryption status';
       setError(message);
@@ -55,6 +62,20 @@ export default function EncryptionKeyPage() {
     }
   };
 
+  const handleStartReencryption = async () => {
+    try {
+      setStartingReencryption(true);
+      const job = await AdminSecurityService.startReencryption();
+      toast.success(`Re-encryption ${job.status === 'running' ? 'is running' : 'queued'}`);
+      setReencryption(await AdminSecurityService.getReencryptionStatus());
+    } catch (err) {
+      const message = err instanceof Error ? err.message : 'Failed to queue re-encryption';
+      toast.error(message);
+    } finally {
+      setStartingReencryption(false);
+    }
+  };
+
   const handleRotateKey = async () => {
     if (!data) return;
 
@@ -329,6 +350,77 @@ export default function EncryptionKeyPage() {
           </Card>
         </div>
 
+        {/* Re-encryption Progress */}
+        {reencryption && (
+          <Card>
+            <CardHeader>
+              <CardTitle className="text-lg flex items-center justify-between">
+                <span className="flex items-center gap-2">
+                  <RefreshCw className="h-5 w-5" />
+                  Re-encryption Progress
+                </span>
+                <Button
+                  size="sm"
+                  variant="outline"
+                  onClick={handleStartReencryption}
+                  disabled={startingReencryption || reencryption.job?.status === 'running'}
+                >
+                  {startingReencryption ? 'Queueing...' : 'Re-encrypt Now'}
+                </Button>
+              </CardTitle>
+              <CardDescription>
+                {reencryption.active_key_version === 0
+                  ? 'No data key active yet: data is encrypted with the master key'
+                  : `${reencryption.percent_migrated}% of encrypted rows on key v${reencryption.active_key_version}`}
+                {reencryption.job && (
+                  <>
+                    {' · '}Last job {reencryption.job.status}
+                    {reencryption.job.status === 'queued' &&
+                      `, runs ${formatDistanceToNow(new Date(reencryption.job.run_at), { addSuffix: true })}`}
+                    {reencryption.job.completed_at &&
+                      `, ${formatDistanceToNow(new Date(reencryption.job.completed_at), { addSuffix: true })}`}
+                    {reencryption.job.last_error && ` (${reencryption.job.last_error})`}
+                  </>
+                )}
+              </CardDescription>
+            </CardHeader>
+            <CardContent className="p-0">
+              <div className="overflow-x-auto">
+                <Table>
+                  <TableHeader>
+                    <TableRow>
+                      <TableHead>Table</TableHead>
+                      <TableHead className="text-right">Encrypted Rows</TableHead>
+                      <TableHead className="text-right">On Active Key</TableHead>
+                      <TableHead className="w-1/3">Migrated</TableHead>
+                    </TableRow>
+                  </TableHeader>
+                  <TableBody>
+                    {reencryption.tables.map((table) => (
+                      <TableRow key={table.table}>
+                        <TableCell className="font-mono text-sm">{table.table}</TableCell>
+                        <TableCell className="text-right text-sm">{table.encrypted_rows.toLocaleString()}</TableCell>
+                        <TableCell className="text-right text-sm">{table.current_rows.toLocaleString()}</TableCell>
+                        <TableCell>
+                          <div className="flex items-center gap-2">
+                            <div className="h-2 flex-1 rounded-full bg-gray-200 dark:bg-gray-800">
+                              <div
+                                className={`h-2 rounded-full ${table.percent_migrated >= 100 ? 'bg-green-500' : 'bg-blue-500'}`}
+                                style={{ width: `${table.percent_migrated}%` }}
+                              />
+                            </div>
+                            <span className="w-14 text-right text-sm">{table.percent_migrated}%</span>
+                          </div>
+                        </TableCell>
+                      </TableRow>
+                    ))}
+                  </TableBody>
+                </Table>
+              </div>
+            </CardContent>
+          </Card>
+        )}
+
         {/* All Keys Timeline */}
         <Card>
           <CardHeader>
@@ -30,6 +30,7 @@ import { useRouter, useParams } from 'next/navigation';
 import { RagContextViewer } from '@/components/regulatory/RagContextViewer';
 import { BlockchainAuditTrail } from '@/components/regulatory/BlockchainAuditTrail';
 import { SignatureVerification } from '@/components/regulatory/SignatureVerification';
+import { SignatureReauthModal } from '@/components/regulatory/SignatureReauthModal';
 
 export default function DocumentDetailPage() {
   const router = useRouter();
@@ -40,6 +41,7 @@ export default function DocumentDetailPage() {
   const [verification, setVerification] = useState<VerificationResult | null>(null);
   const [isLoading, setIsLoading] = useState(true);
   const [isApproving, setIsApproving] = useState(false);
+  const [showSignDialog, setShowSignDialog] = useState(false);
 
   useEffect(() => {
     if (documentId) {
@@ -64,12 +66,13 @@ export default function DocumentDetailPage() {
     }
   };
 
-  const handleApprove = async () => {
+  const handleApprove = async (password: string, totpCode?: string) => {
     if (!document) return;
 
     try {
       setIsApproving(true);
-      await regulatoryApi.approve(document.id);
+      await regulatoryApi.approve(document.id, password, totpCode);
+      setShowSignDialog(false);
       toast.success('Document approved successfully!');
       await loadDocument(); // Reload to get updated data
     } catch (error) {
@@ -163,7 +166,7 @@ export default function DocumentDetailPage() {
             </Button>
             {document.status === 'draft' && (
               <Button
-                onClick={handleApprove}
+                onClick={() => setShowSignDialog(true)}
                 disabled={isApproving}
                 className="bg-green-600 hover:bg-green-700"
               >
@@ -349,6 +352,13 @@ export default function DocumentDetailPage() {
           </Alert>
         )}
       </div>
+
+      <SignatureReauthModal
+        isOpen={showSignDialog}
+        isSigning={isApproving}
+        onConfirm={handleApprove}
+        onCancel={() => setShowSignDialog(false)}
+      />
     </DashboardLayout>
   );
 }
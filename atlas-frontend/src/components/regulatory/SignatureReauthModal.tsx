@@ -0,0 +1,102 @@
+'use client';
+
+import { useState, useEffect } from 'react';
+import { Button } from '@/components/ui/button';
+import { Input } from '@/components/ui/input';
+import { Label } from '@/components/ui/label';
+import {
+  Dialog,
+  DialogContent,
+  DialogDescription,
+  DialogHeader,
+  DialogTitle,
+  DialogFooter,
+} from '@/components/ui/dialog';
+import { Loader2, Shield } from 'lucide-react';
+
+interface SignatureReauthModalProps {
+  isOpen: boolean;
+  isSigning: boolean;
+  onConfirm: (password: string, totpCode?: string) => void;
+  onCancel: () => void;
+}
+
+// 21 CFR Part 11: the signer re-authenticates at signing
+export function SignatureReauthModal({ isOpen, isSigning, onConfirm, onCancel }: SignatureReauthModalProps) {
+  const [password, setPassword] = useState('');
+  const [totpCode, setTotpCode] = useState('');
+
+  // Never keep credentials around between signatures
+  useEffect(() => {
+    if (!isOpen) {
+      setPassword('');
+      setTotpCode('');
+    }
+  }, [isOpen]);
+
+  const handleSubmit = (e: React.FormEvent) => {
+    e.preventDefault();
+    if (!password || isSigning) return;
+    onConfirm(password, totpCode.trim() || undefined);
+  };
+
+  return (
+    <Dialog open={isOpen} onOpenChange={(open) => !open && !isSigning && onCancel()}>
+      <DialogContent className="sm:max-w-[450px]">
+        <form onSubmit={handleSubmit}>
+          <DialogHeader>
+            <DialogTitle className="flex items-center gap-2">
+              <Shield className="h-5 w-5 text-green-600" />
+              Sign Approval
+            </DialogTitle>
+            <DialogDescription>
+              Enter your password to sign this approval. Your signature is recorded in the audit ledger.
+            </DialogDescription>
+          </DialogHeader>
+
+          <div className="space-y-4 py-4">
+            <div className="space-y-2">
+              <Label htmlFor="signature-password">Password</Label>
+              <Input
+                id="signature-password"
+                type="password"
+                autoComplete="current-password"
+                value={password}
+                onChange={(e) => setPassword(e.target.value)}
+                disabled={isSigning}
+                autoFocus
+              />
+            </div>
+            <div className="space-y-2">
+              <Label htmlFor="signature-totp">MFA code</Label>
+              <Input
+                id="signature-totp"
+                type="text"
+                inputMode="numeric"
+                autoComplete="one-time-code"
+                placeholder="Leave empty if MFA is not enabled"
+                value={totpCode}
+                onChange={(e) => setTotpCode(e.target.value.replace(/\D/g, '').slice(0, 6))}
+                disabled={isSigning}
+              />
+            </div>
+          </div>
+
+          <DialogFooter className="gap-2">
+            <Button type="button" variant="outline" onClick={onCancel} disabled={isSigning}>
+              Cancel
+            </Button>
+            <Button
+              type="submit"
+              disabled={!password || isSigning}
+              className="bg-green-600 hover:bg-green-700"
+            >
+              {isSigning && <Loader2 className="h-4 w-4 mr-2 animate-spin" />}
+              Sign
+            </Button>
+          </DialogFooter>
+        </form>
+      </DialogContent>
+    </Dialog>
+  );
+}
@@ -132,11 +132,11 @@ export const regulatoryApi = {
   getById: (id: string) =>
     apiClient.get<GeneratedDocument>(`/api/regulatory/documents/${id}`),
 
-  // Approve a document (creates second signature in blockchain)
-  approve: (id: string, comments?: string) =>
+  // Approve a document (approval e-signature; the password re-authenticates the signer)
+  approve: (id: string, password: string, totpCode?: string, comments?: string) =>
     apiClient.post<{ success: boolean; approved_at: string; approved_by: string; document_id: string }>(
       `/api/regulatory/documents/${id}/approve`,
-      comments ? { comments } : {}
+      { password, totp_code: totpCode || undefined, comments }
     ),
 
   // Verify document signatures and blockchain integrity
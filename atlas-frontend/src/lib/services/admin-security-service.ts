@@ -22,6 +22,8 @@ import type {
   EncryptionStatus,
   KeyRotationRequest,
   EncryptionKeyInfo,
+  ReencryptionJob,
+  ReencryptionStatus,
   MetricsSummary,
   RateLimitStatus,
 } from '@/types/admin-security';
@@ -149,6 +151,28 @@ export class AdminSecurityService {
     );
   }
 
+  /**
+   * GET /api/admin/security/encryption/reencryption
+   *
+   * Fetches the share of encrypted rows per table already on the active key
+   *
+   * @returns Per-table progress and the latest re-encryption job
+   */
+  static async getReencryptionStatus(): Promise<ReencryptionStatus> {
+    return apiClient.get<ReencryptionStatus>('/api/admin/security/encryption/reencryption');
+  }
+
+  /**
+   * POST /api/admin/security/encryption/reencrypt
+   *
+   * Queues re-encryption of existing data with the active key (Superadmin only)
+   *
+   * @returns The queued job, or the one already queued for the active key
+   */
+  static async startReencryption(): Promise<ReencryptionJob> {
+    return apiClient.post<ReencryptionJob>('/api/admin/security/encryption/reencrypt', {});
+  }
+
   // ==========================================================================
   // System Metrics
   // ==========================================================================
@@ -59,6 +59,7 @@ export interface AdminStats {
   total_admins: number;
   total_inventory_items: number;
   total_transactions: number;
+  counts_updated_at: string | null;
   recent_signups: RecentSignup[];
   system_health: SystemHealth;
 }
@@ -127,6 +127,30 @@ export interface KeyRotationRequest {
   reason?: string;
 }
 
+export interface TableReencryptionProgress {
+  table: string;
+  encrypted_rows: number;
+  current_rows: number;       // Rows already on the active key
+  percent_migrated: number;
+}
+
+export interface ReencryptionJob {
+  id: string;
+  status: 'queued' | 'running' | 'succeeded' | 'failed' | 'cancelled';
+  attempts: number;
+  max_attempts: number;
+  run_at: string;             // ISO 8601 datetime
+  last_error?: string | null;
+  completed_at?: string | null;
+}
+
+export interface ReencryptionStatus {
+  active_key_version: number; // 0 = ENCRYPTION_KEY itself, no data key active yet
+  percent_migrated: number;
+  tables: TableReencryptionProgress[];
+  job: ReencryptionJob | null;
+}
+
 // ============================================================================
 // Metrics Types
 // ============================================================================
@@ -0,0 +1,67 @@
+// ERP Bulk Sync Benchmarks
+// In-memory cost of a 10k-item ERP -> Atlas sync: conflict planning per upsert chunk and
+// the SAP batch filters. Run with: cargo bench --bench erp_bulk_sync
+
+use std::collections::HashMap;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use uuid::Uuid;
+
+use atlas_pharma::services::erp::bulk_sync::{
+    plan_quantity_changes, QuantityChange, SAP_MATERIAL_BATCH_SIZE, UPSERT_CHUNK_SIZE,
+};
+use atlas_pharma::services::erp::sap_client::material_filter;
+use atlas_pharma::services::erp::ConflictResolution;
+
+const ITEMS: usize = 10_000;
+
+fn changes() -> (Vec<QuantityChange>, HashMap<Uuid, i32>) {
+    let changes = (0..ITEMS)
+        .map(|i| QuantityChange {
+            mapping_id: Uuid::new_v4(),
+            inventory_id: Uuid::new_v4(),
+            erp_quantity: (i % 500) as i32,
+        })
+        .collect::<Vec<_>>();
+
+    // Every third item differs from Atlas
+    let atlas = changes
+        .iter()
+        .enumerate()
+        .map(|(i, c)| (c.inventory_id, if i % 3 == 0 { c.erp_quantity + 1 } else { c.erp_quantity }))
+        .collect();
+
+    (changes, atlas)
+}
+
+fn bench_plan(c: &mut Criterion) {
+    let (changes, atlas) = changes();
+
+    for (name, resolution) in [
+        ("plan_10k_erp_wins", ConflictResolution::ErpWins),
+        ("plan_10k_manual", ConflictResolution::Manual),
+    ] {
+        c.bench_function(name, |b| {
+            b.iter(|| {
+                for chunk in changes.chunks(UPSERT_CHUNK_SIZE) {
+                    black_box(plan_quantity_changes(black_box(chunk), &atlas, &resolution));
+                }
+            })
+        });
+    }
+}
+
+fn bench_sap_filters(c: &mut Criterion) {
+    let materials = (0..ITEMS).map(|i| format!("MAT-{:06}", i)).collect::<Vec<_>>();
+
+    c.bench_function("sap_material_filters_10k", |b| {
+        b.iter(|| {
+            for batch in materials.chunks(SAP_MATERIAL_BATCH_SIZE) {
+                black_box(material_filter("1000", black_box(batch)));
+            }
+        })
+    });
+}
+
+criterion_group!(benches, bench_plan, bench_sap_filters);
+criterion_main!(benches);
@@ -0,0 +1,55 @@
+-- Product Image Management
+-- Lets admins and verified sellers attach images to catalog pharmaceuticals.
+-- Seller uploads go through moderation before they are served publicly.
+
+-- ============================================================================
+-- STEP 1: Image records
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS pharmaceutical_images (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    pharmaceutical_id UUID NOT NULL REFERENCES pharmaceuticals(id) ON DELETE CASCADE,
+    uploaded_by UUID REFERENCES users(id) ON DELETE SET NULL,
+
+    -- Storage (paths are relative to FILE_STORAGE_PATH/product_images)
+    file_path TEXT NOT NULL,
+    thumbnail_path TEXT NOT NULL,
+    original_filename VARCHAR(255),
+    content_type VARCHAR(50) NOT NULL,
+    file_size_bytes BIGINT NOT NULL CHECK (file_size_bytes > 0),
+    file_hash VARCHAR(64) NOT NULL,  -- SHA-256, doubles as the HTTP ETag
+    width INTEGER NOT NULL,
+    height INTEGER NOT NULL,
+
+    alt_text VARCHAR(255),
+    is_primary BOOLEAN NOT NULL DEFAULT FALSE,
+
+    -- Moderation
+    moderation_status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (moderation_status IN ('pending', 'approved', 'rejected')),
+    moderated_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    moderated_at TIMESTAMPTZ,
+    rejection_reason TEXT,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- ============================================================================
+-- STEP 2: Indexes
+-- ============================================================================
+
+CREATE INDEX IF NOT EXISTS idx_pharma_images_pharmaceutical
+ON pharmaceutical_images (pharmaceutical_id, created_at DESC);
+
+-- Moderation queue
+CREATE INDEX IF NOT EXISTS idx_pharma_images_pending
+ON pharmaceutical_images (created_at)
+WHERE moderation_status = 'pending';
+
+-- At most one primary image per pharmaceutical
+CREATE UNIQUE INDEX IF NOT EXISTS idx_pharma_images_primary_unique
+ON pharmaceutical_images (pharmaceutical_id)
+WHERE is_primary = TRUE;
+
+COMMENT ON TABLE pharmaceutical_images IS 'Moderated product images attached to catalog pharmaceuticals';
@@ -0,0 +1,103 @@
+-- Catalog Change Subscriptions
+-- Users subscribe to specific NDCs (OpenFDA) or EU numbers (EMA) and are notified
+-- in-app and/or by webhook when a sync run changes the status of those records.
+
+-- ============================================================================
+-- STEP 1: Subscriptions
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS catalog_subscriptions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+
+    -- What to watch
+    source VARCHAR(20) NOT NULL CHECK (source IN ('openfda', 'ema')),
+    identifier VARCHAR(50) NOT NULL,  -- product_ndc or eu_number
+    change_types TEXT[] NOT NULL DEFAULT ARRAY['status_change', 'discontinuation', 'recall'],
+
+    -- How to deliver
+    notify_in_app BOOLEAN NOT NULL DEFAULT TRUE,
+    webhook_url TEXT,
+    webhook_secret_encrypted TEXT,  -- AES-256-GCM, used to sign webhook payloads
+
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    last_notified_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT uq_catalog_subscription UNIQUE (user_id, source, identifier),
+    CONSTRAINT chk_catalog_subscription_channel CHECK (notify_in_app OR webhook_url IS NOT NULL)
+);
+
+-- Sync paths look up subscriptions by (source, identifier)
+CREATE INDEX IF NOT EXISTS idx_catalog_subscriptions_lookup
+ON catalog_subscriptions (source, identifier)
+WHERE is_active = TRUE;
+
+CREATE INDEX IF NOT EXISTS idx_catalog_subscriptions_user
+ON catalog_subscriptions (user_id, created_at DESC);
+
+-- ============================================================================
+-- STEP 2: Detected changes
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS catalog_change_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    source VARCHAR(20) NOT NULL CHECK (source IN ('openfda', 'ema')),
+    identifier VARCHAR(50) NOT NULL,
+    product_name TEXT,
+    change_type VARCHAR(30) NOT NULL
+        CHECK (change_type IN ('status_change', 'discontinuation', 'recall')),
+    old_value TEXT,
+    new_value TEXT,
+    details JSONB NOT NULL DEFAULT '{}'::jsonb,
+    sync_log_id UUID,  -- openfda_sync_log.id or ema_sync_log.id
+    detected_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    dispatched_at TIMESTAMPTZ  -- NULL until subscribers have been notified
+);
+
+CREATE INDEX IF NOT EXISTS idx_catalog_change_events_pending
+ON catalog_change_events (detected_at)
+WHERE dispatched_at IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_catalog_change_events_identifier
+ON catalog_change_events (source, identifier, detected_at DESC);
+
+-- ============================================================================
+-- STEP 3: Delivery log
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS catalog_subscription_deliveries (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    subscription_id UUID NOT NULL REFERENCES catalog_subscriptions(id) ON DELETE CASCADE,
+    event_id UUID NOT NULL REFERENCES catalog_change_events(id) ON DELETE CASCADE,
+    channel VARCHAR(20) NOT NULL CHECK (channel IN ('in_app', 'webhook')),
+    status VARCHAR(20) NOT NULL CHECK (status IN ('delivered', 'failed')),
+    http_status INTEGER,
+    error_message TEXT,
+    delivered_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_catalog_deliveries_subscription
+ON catalog_subscription_deliveries (subscription_id, delivered_at DESC);
+
+-- ============================================================================
+-- STEP 4: Allow catalog change alerts in the notification center
+-- ============================================================================
+
+ALTER TABLE alert_notifications DROP CONSTRAINT IF EXISTS alert_notifications_alert_type_check;
+ALTER TABLE alert_notifications ADD CONSTRAINT alert_notifications_alert_type_check
+CHECK (alert_type IN (
+    'expiry_warning',
+    'expiry_critical',
+    'low_stock',
+    'watchlist_match',
+    'price_drop',
+    'new_inquiry',
+    'inquiry_message',
+    'catalog_change',
+    'system'
+));
+
+COMMENT ON TABLE catalog_subscriptions IS 'User subscriptions to status changes on specific OpenFDA/EMA catalog records';
+COMMENT ON TABLE catalog_change_events IS 'Status changes detected by diffing catalog sync upserts';
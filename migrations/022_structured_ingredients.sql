@@ -0,0 +1,40 @@
+-- Structured Ingredients
+-- Stores active and inactive ingredients per pharmaceutical, tagged with allergen
+-- classes, so catalog and marketplace search can filter e.g. lactose-free products.
+
+-- ============================================================================
+-- STEP 1: Ingredient rows
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS pharmaceutical_ingredients (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    pharmaceutical_id UUID NOT NULL REFERENCES pharmaceuticals(id) ON DELETE CASCADE,
+    role VARCHAR(10) NOT NULL CHECK (role IN ('active', 'inactive')),
+    name TEXT NOT NULL,
+    strength VARCHAR(100),
+    allergens TEXT[] NOT NULL DEFAULT '{}',  -- lactose, gelatin, gluten, peanut, soy, egg, sulfite, azo_dye
+    position INTEGER NOT NULL DEFAULT 0,     -- order on the label
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_pharma_ingredients_pharmaceutical
+ON pharmaceutical_ingredients (pharmaceutical_id, role, position);
+
+-- free_of filters use array overlap (&&)
+CREATE INDEX IF NOT EXISTS idx_pharma_ingredients_allergens
+ON pharmaceutical_ingredients USING GIN (allergens);
+
+CREATE INDEX IF NOT EXISTS idx_pharma_ingredients_name
+ON pharmaceutical_ingredients (LOWER(name));
+
+-- ============================================================================
+-- STEP 2: Excipient declaration marker
+-- ============================================================================
+
+-- A product with no inactive-ingredient rows is only "free of" anything once its
+-- excipient list has actually been recorded (some injectables have none).
+ALTER TABLE pharmaceuticals
+ADD COLUMN IF NOT EXISTS ingredients_declared_at TIMESTAMPTZ;
+
+COMMENT ON TABLE pharmaceutical_ingredients IS 'Structured active/inactive ingredients with allergen classification';
+COMMENT ON COLUMN pharmaceuticals.ingredients_declared_at IS 'When the ingredient list was last recorded; NULL means excipients unknown';
@@ -0,0 +1,78 @@
+-- Product Domains & Medical Device Catalog
+-- Splits the pharmaceutical catalog into human drug / veterinary / medical device
+-- segments and adds an OpenFDA device UDI (GUDID) reference catalog.
+
+-- ============================================================================
+-- STEP 1: Product domain on pharmaceuticals
+-- ============================================================================
+
+ALTER TABLE pharmaceuticals
+ADD COLUMN IF NOT EXISTS product_domain VARCHAR(20) NOT NULL DEFAULT 'human_drug'
+    CHECK (product_domain IN ('human_drug', 'veterinary', 'medical_device')),
+ADD COLUMN IF NOT EXISTS udi_di VARCHAR(50),            -- GUDID primary device identifier
+ADD COLUMN IF NOT EXISTS device_class VARCHAR(5),       -- FDA device class: 1, 2, 3, U, N, F
+ADD COLUMN IF NOT EXISTS target_species TEXT[];         -- veterinary products only
+
+-- Device identifiers only make sense on device listings, and every device needs one
+ALTER TABLE pharmaceuticals
+ADD CONSTRAINT chk_pharma_device_identifiers CHECK (
+    (product_domain = 'medical_device' AND udi_di IS NOT NULL)
+    OR (product_domain <> 'medical_device' AND udi_di IS NULL AND device_class IS NULL)
+);
+
+ALTER TABLE pharmaceuticals
+ADD CONSTRAINT chk_pharma_target_species CHECK (
+    target_species IS NULL OR product_domain = 'veterinary'
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_pharma_udi_di
+ON pharmaceuticals (udi_di)
+WHERE udi_di IS NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_pharma_product_domain
+ON pharmaceuticals (product_domain);
+
+-- ============================================================================
+-- STEP 2: OpenFDA device UDI catalog (reference data, like openfda_catalog)
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS openfda_device_catalog (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    primary_di VARCHAR(50) NOT NULL UNIQUE,
+    issuing_agency VARCHAR(20),                 -- GS1, HIBCC, ICCBBA
+    brand_name TEXT,
+    version_model_number TEXT,
+    catalog_number TEXT,
+    company_name TEXT,
+    device_description TEXT,
+    device_class VARCHAR(5),
+    product_codes TEXT[],
+    gmdn_terms JSONB,
+    is_rx BOOLEAN,
+    is_otc BOOLEAN,
+    is_single_use BOOLEAN,
+    is_sterile BOOLEAN,
+    mri_safety TEXT,
+    commercial_distribution_status TEXT,
+    record_status TEXT,
+    publish_date DATE,
+    identifiers JSONB,                          -- all DIs (primary, package, unit of use)
+    last_synced_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_device_catalog_brand
+ON openfda_device_catalog (LOWER(brand_name));
+
+CREATE INDEX IF NOT EXISTS idx_device_catalog_company
+ON openfda_device_catalog (LOWER(company_name));
+
+CREATE INDEX IF NOT EXISTS idx_device_catalog_class
+ON openfda_device_catalog (device_class);
+
+-- Device syncs are logged alongside drug syncs
+COMMENT ON COLUMN openfda_sync_log.sync_type IS 'Type of sync: full, incremental, manual, device';
+
+COMMENT ON TABLE openfda_device_catalog IS 'OpenFDA device/UDI (GUDID) records for device listings';
+COMMENT ON COLUMN pharmaceuticals.product_domain IS 'Catalog segment: human_drug, veterinary or medical_device';
@@ -0,0 +1,15 @@
+-- OpenFDA Delta Sync Checkpoints
+-- Completed syncs record the dataset's meta.last_updated and the record count of each
+-- brand_name partition. Delta syncs compare against the latest checkpoint and only
+-- refetch partitions whose counts changed (plus recently started listings).
+
+ALTER TABLE openfda_sync_log
+ADD COLUMN IF NOT EXISTS checkpoint JSONB;
+
+-- Delta syncs look up the most recent completed checkpoint
+CREATE INDEX IF NOT EXISTS idx_openfda_sync_log_checkpoint
+ON openfda_sync_log (sync_completed_at DESC)
+WHERE status = 'completed' AND checkpoint IS NOT NULL;
+
+COMMENT ON COLUMN openfda_sync_log.checkpoint IS 'Delta checkpoint: {dataset_last_updated, partition_totals, synced_on}';
+COMMENT ON COLUMN openfda_sync_log.sync_type IS 'Type of sync: full, manual, scheduled, delta, device';
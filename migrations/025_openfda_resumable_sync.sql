@@ -0,0 +1,13 @@
+-- Resumable OpenFDA Sync
+-- Partitioned syncs periodically persist their position (partition list, current
+-- partition and skip offset, running totals, seen-NDC bloom filter) so a run that was
+-- interrupted by a restart or failure can be resumed instead of starting over.
+
+ALTER TABLE openfda_sync_log
+ADD COLUMN IF NOT EXISTS resume_state JSONB,
+ADD COLUMN IF NOT EXISTS last_heartbeat_at TIMESTAMPTZ,
+ADD COLUMN IF NOT EXISTS resume_count INTEGER NOT NULL DEFAULT 0;
+
+COMMENT ON COLUMN openfda_sync_log.resume_state IS 'Resume checkpoint of an unfinished sync; cleared on completion';
+COMMENT ON COLUMN openfda_sync_log.last_heartbeat_at IS 'Last progress update; in_progress syncs without a recent heartbeat were interrupted';
+COMMENT ON COLUMN openfda_sync_log.resume_count IS 'Number of times this sync was resumed';
@@ -0,0 +1,51 @@
+-- OpenFDA Drug Enforcement (Recalls)
+-- Reference copy of https://api.fda.gov/drug/enforcement.json. Each recall is linked
+-- to product NDCs (from the openfda section and NDCs quoted in the product
+-- description / code info) so recalls can be matched against the NDC catalog and
+-- pharmaceuticals.ndc_code.
+
+CREATE TABLE IF NOT EXISTS openfda_recalls (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    recall_number VARCHAR(50) NOT NULL UNIQUE,   -- e.g. D-0123-2024
+    event_id VARCHAR(20),
+    status VARCHAR(30),                          -- Ongoing, Completed, Terminated, Pending
+    classification VARCHAR(20),                  -- Class I, Class II, Class III
+    product_type VARCHAR(30),
+    product_description TEXT,
+    reason_for_recall TEXT,
+    code_info TEXT,                              -- lot numbers / expiry dates
+    product_quantity TEXT,
+    distribution_pattern TEXT,
+    recalling_firm TEXT,
+    city TEXT,
+    state TEXT,
+    country TEXT,
+    voluntary_mandated TEXT,
+    initial_firm_notification TEXT,
+    recall_initiation_date DATE,
+    report_date DATE,
+    center_classification_date DATE,
+    termination_date DATE,
+    product_ndcs TEXT[] NOT NULL DEFAULT '{}',   -- labeler-product NDCs (catalog linkage)
+    package_ndcs TEXT[] NOT NULL DEFAULT '{}',
+    openfda_data JSONB,
+    last_synced_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- NDC linkage lookups use array containment / overlap
+CREATE INDEX IF NOT EXISTS idx_openfda_recalls_product_ndcs
+ON openfda_recalls USING GIN (product_ndcs);
+
+CREATE INDEX IF NOT EXISTS idx_openfda_recalls_report_date
+ON openfda_recalls (report_date DESC);
+
+CREATE INDEX IF NOT EXISTS idx_openfda_recalls_status_class
+ON openfda_recalls (status, classification);
+
+CREATE INDEX IF NOT EXISTS idx_openfda_recalls_firm
+ON openfda_recalls (LOWER(recalling_firm));
+
+COMMENT ON TABLE openfda_recalls IS 'OpenFDA drug enforcement reports (recalls) linked to product NDCs';
+COMMENT ON COLUMN openfda_sync_log.sync_type IS 'Type of sync: full, manual, scheduled, delta, device, recall';
@@ -0,0 +1,36 @@
+-- EMA Product Information Documents
+-- Optional local copies of ePI / SmPC / PIL documents referenced by ema_catalog.
+-- Content is stored encrypted on disk (EncryptedFileStorage); the database keeps the
+-- plaintext SHA-256 (to skip unchanged documents) and extracted text for search.
+
+CREATE TABLE IF NOT EXISTS ema_documents (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    eu_number VARCHAR(50) NOT NULL,
+    language_code VARCHAR(10) NOT NULL,
+    document_type VARCHAR(10) NOT NULL CHECK (document_type IN ('epi', 'smpc', 'pil')),
+    source_url TEXT NOT NULL,
+    storage_path TEXT NOT NULL,               -- relative to the EMA document storage root
+    content_hash VARCHAR(64) NOT NULL,        -- SHA-256 of the plaintext content
+    content_type VARCHAR(100),
+    size_bytes BIGINT NOT NULL,
+    extracted_text TEXT,                      -- NULL when the format is not supported (e.g. PDF)
+    fetched_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),   -- last download attempt that succeeded
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),   -- last time the content hash changed
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    -- 'simple' config: documents come in all EU languages
+    search_vector tsvector GENERATED ALWAYS AS (
+        to_tsvector('simple', coalesce(extracted_text, ''))
+    ) STORED,
+
+    UNIQUE (eu_number, language_code, document_type)
+);
+
+CREATE INDEX IF NOT EXISTS idx_ema_documents_eu_number
+ON ema_documents (eu_number);
+
+CREATE INDEX IF NOT EXISTS idx_ema_documents_search
+ON ema_documents USING GIN (search_vector);
+
+COMMENT ON TABLE ema_documents IS 'Downloaded EMA ePI/SmPC/PIL documents (encrypted at rest) with extracted text';
+COMMENT ON COLUMN ema_documents.content_hash IS 'SHA-256 of plaintext; unchanged documents are not rewritten';
@@ -0,0 +1,104 @@
+-- Additional regulator catalogs: UK MHRA, Health Canada (DPD) and Swissmedic
+-- All three share one catalog table and one sync log, keyed by `source`, so new
+-- regulators only need a connector rather than their own tables.
+
+CREATE TABLE IF NOT EXISTS regulator_catalog (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+
+    -- Source and identifier
+    source VARCHAR(30) NOT NULL,            -- mhra, health_canada, swissmedic
+    source_id VARCHAR(100) NOT NULL,        -- PL number, DIN, Swissmedic authorization + dosage number
+    country VARCHAR(2) NOT NULL,            -- GB, CA, CH
+
+    -- Product
+    product_name TEXT NOT NULL,
+    active_ingredients TEXT[] NOT NULL DEFAULT '{}',
+    strength TEXT,
+    dosage_form TEXT,
+    route_of_administration TEXT[] NOT NULL DEFAULT '{}',
+    atc_code VARCHAR(20),
+
+    -- Authorization
+    authorization_holder TEXT,
+    authorization_status VARCHAR(50),
+    authorization_date DATE,
+
+    -- Raw source data for reference
+    raw_data JSONB,
+
+    -- Cache management
+    last_synced_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
+
+    -- Multilingual sources (CH, CA) use the 'simple' configuration
+    search_vector tsvector GENERATED ALWAYS AS (
+        setweight(to_tsvector('simple', coalesce(product_name, '')), 'A') ||
+        setweight(to_tsvector('simple', array_to_string(active_ingredients, ' ')), 'A') ||
+        setweight(to_tsvector('simple', coalesce(authorization_holder, '')), 'B') ||
+        setweight(to_tsvector('simple', coalesce(source_id, '')), 'B')
+    ) STORED,
+
+    UNIQUE (source, source_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_regulator_catalog_source ON regulator_catalog(source);
+CREATE INDEX IF NOT EXISTS idx_regulator_catalog_search ON regulator_catalog USING gin(search_vector);
+CREATE INDEX IF NOT EXISTS idx_regulator_catalog_ingredients ON regulator_catalog USING gin(active_ingredients);
+CREATE INDEX IF NOT EXISTS idx_regulator_catalog_atc ON regulator_catalog(source, atc_code);
+CREATE INDEX IF NOT EXISTS idx_regulator_catalog_last_synced ON regulator_catalog(source, last_synced_at);
+
+CREATE TRIGGER trigger_update_regulator_catalog_updated_at
+    BEFORE UPDATE ON regulator_catalog
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+-- Shared sync log for regulator catalogs
+CREATE TABLE IF NOT EXISTS catalog_sync_log (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    source VARCHAR(30) NOT NULL,
+    sync_type VARCHAR(20) NOT NULL DEFAULT 'full',
+    status VARCHAR(20) NOT NULL DEFAULT 'in_progress', -- in_progress, completed, failed
+
+    sync_started_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    sync_completed_at TIMESTAMP WITH TIME ZONE,
+    last_heartbeat_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+
+    records_fetched INTEGER NOT NULL DEFAULT 0,
+    records_inserted INTEGER NOT NULL DEFAULT 0,
+    records_updated INTEGER NOT NULL DEFAULT 0,
+    records_skipped INTEGER NOT NULL DEFAULT 0,
+    records_failed INTEGER NOT NULL DEFAULT 0,
+    records_removed INTEGER NOT NULL DEFAULT 0,
+
+    api_response_time_ms INTEGER,
+    processing_time_ms INTEGER,
+    error_message TEXT,
+
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_catalog_sync_log_source ON catalog_sync_log(source, sync_started_at DESC);
+CREATE INDEX IF NOT EXISTS idx_catalog_sync_log_status ON catalog_sync_log(status);
+
+-- Sync history across every catalog source in one shape
+CREATE OR REPLACE VIEW catalog_sync_history AS
+SELECT id, 'openfda' || CASE WHEN sync_type IN ('device', 'recall') THEN '_' || sync_type ELSE '' END AS source,
+       sync_type, status, sync_started_at, sync_completed_at,
+       records_fetched, records_inserted, records_updated, records_skipped, records_failed,
+       error_message
+FROM openfda_sync_log
+UNION ALL
+SELECT id, 'ema' AS source, sync_type, status, sync_started_at, sync_completed_at,
+       records_fetched, records_inserted, records_updated, records_skipped, records_failed,
+       error_message
+FROM ema_sync_log
+UNION ALL
+SELECT id, source, sync_type, status, sync_started_at, sync_completed_at,
+       records_fetched, records_inserted, records_updated, records_skipped, records_failed,
+       error_message
+FROM catalog_sync_log;
+
+COMMENT ON TABLE regulator_catalog IS 'Cached product catalogs from MHRA, Health Canada and Swissmedic';
+COMMENT ON TABLE catalog_sync_log IS 'Sync runs for regulator catalogs (one row per source sync)';
+COMMENT ON VIEW catalog_sync_history IS 'Sync history across OpenFDA, EMA and regulator catalogs';
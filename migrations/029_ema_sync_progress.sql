@@ -0,0 +1,26 @@
+-- EMA Sync Progress Tracking
+-- Brings ema_sync_log to parity with openfda_sync_log: live progress, heartbeat
+-- (so crashed runs don't block new ones) and cancellation
+
+ALTER TABLE ema_sync_log
+ADD COLUMN IF NOT EXISTS total_expected INTEGER DEFAULT 0,
+ADD COLUMN IF NOT EXISTS records_processed INTEGER DEFAULT 0,
+ADD COLUMN IF NOT EXISTS current_batch INTEGER DEFAULT 0,
+ADD COLUMN IF NOT EXISTS total_batches INTEGER DEFAULT 0,
+ADD COLUMN IF NOT EXISTS last_heartbeat_at TIMESTAMP WITH TIME ZONE,
+ADD COLUMN IF NOT EXISTS cancelled_at TIMESTAMP WITH TIME ZONE,
+ADD COLUMN IF NOT EXISTS cancelled_by UUID REFERENCES users(id);
+
+-- Index for finding active syncs
+CREATE INDEX IF NOT EXISTS idx_ema_sync_log_active
+ON ema_sync_log(status)
+WHERE status = 'in_progress';
+
+COMMENT ON COLUMN ema_sync_log.total_expected IS 'Total records expected (sync limit, or the API total when smaller)';
+COMMENT ON COLUMN ema_sync_log.records_processed IS 'Running count of records processed';
+COMMENT ON COLUMN ema_sync_log.current_batch IS 'Current batch number being processed';
+COMMENT ON COLUMN ema_sync_log.total_batches IS 'Estimated total batches based on total_expected';
+COMMENT ON COLUMN ema_sync_log.last_heartbeat_at IS 'Last progress update; in_progress runs without a recent heartbeat are interrupted';
+COMMENT ON COLUMN ema_sync_log.cancelled_at IS 'Timestamp when sync was cancelled';
+COMMENT ON COLUMN ema_sync_log.cancelled_by IS 'User who cancelled the sync';
+COMMENT ON COLUMN ema_sync_log.sync_type IS 'Type of sync: full, incremental, by_language, scheduled';
@@ -0,0 +1,18 @@
+-- Catalog Sync Anomaly Detection
+-- Completed syncs that look wrong (mass changes, nothing written, error spikes) are
+-- marked 'suspect' instead of 'completed'. Suspect runs don't count as the last
+-- successful sync, so refresh checks keep reporting the catalog as stale.
+
+ALTER TABLE openfda_sync_log ADD COLUMN IF NOT EXISTS anomalies JSONB;
+ALTER TABLE ema_sync_log ADD COLUMN IF NOT EXISTS anomalies JSONB;
+ALTER TABLE catalog_sync_log ADD COLUMN IF NOT EXISTS anomalies JSONB;
+
+COMMENT ON COLUMN openfda_sync_log.anomalies IS 'Anomalies that marked the run suspect (NULL for normal runs)';
+COMMENT ON COLUMN ema_sync_log.anomalies IS 'Anomalies that marked the run suspect (NULL for normal runs)';
+COMMENT ON COLUMN catalog_sync_log.anomalies IS 'Anomalies that marked the run suspect (NULL for normal runs)';
+
+COMMENT ON COLUMN ema_sync_log.status IS 'Current status: in_progress, completed, suspect, failed, cancelled';
+
+CREATE INDEX IF NOT EXISTS idx_openfda_sync_log_suspect ON openfda_sync_log(sync_completed_at DESC) WHERE status = 'suspect';
+CREATE INDEX IF NOT EXISTS idx_ema_sync_log_suspect ON ema_sync_log(sync_completed_at DESC) WHERE status = 'suspect';
+CREATE INDEX IF NOT EXISTS idx_catalog_sync_log_suspect ON catalog_sync_log(source, sync_completed_at DESC) WHERE status = 'suspect';
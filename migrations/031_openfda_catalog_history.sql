@@ -0,0 +1,83 @@
+-- OpenFDA Catalog History
+-- Keeps every version of an NDC directory entry (SCD type 2): each row is valid from
+-- `valid_from` until `valid_to` (NULL for the version currently in force). Versions are
+-- recorded by trigger, so every upsert path is covered and unchanged re-syncs add nothing.
+
+CREATE TABLE IF NOT EXISTS openfda_catalog_history (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    product_ndc VARCHAR(20) NOT NULL,
+    version INTEGER NOT NULL,
+    data JSONB NOT NULL,                      -- Catalog entry without ids and sync timestamps
+    valid_from TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    valid_to TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    UNIQUE (product_ndc, version),
+    CHECK (valid_to IS NULL OR valid_to >= valid_from)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_openfda_history_current
+ON openfda_catalog_history(product_ndc)
+WHERE valid_to IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_openfda_history_validity
+ON openfda_catalog_history(product_ndc, valid_from DESC);
+
+CREATE OR REPLACE FUNCTION record_openfda_catalog_history()
+RETURNS TRIGGER AS $$
+DECLARE
+    snapshot JSONB;
+    current_version openfda_catalog_history%ROWTYPE;
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        UPDATE openfda_catalog_history SET valid_to = NOW()
+        WHERE product_ndc = OLD.product_ndc AND valid_to IS NULL;
+        RETURN NULL;
+    END IF;
+
+    snapshot := to_jsonb(NEW) - 'id' - 'last_synced_at' - 'created_at' - 'updated_at' - 'search_vector';
+
+    SELECT * INTO current_version
+    FROM openfda_catalog_history
+    WHERE product_ndc = NEW.product_ndc AND valid_to IS NULL
+    FOR UPDATE;
+
+    IF FOUND THEN
+        IF current_version.data = snapshot THEN
+            RETURN NULL;
+        END IF;
+
+        UPDATE openfda_catalog_history SET valid_to = NOW() WHERE id = current_version.id;
+    END IF;
+
+    INSERT INTO openfda_catalog_history (product_ndc, version, data, valid_from)
+    VALUES (NEW.product_ndc, COALESCE(current_version.version, 0) + 1, snapshot, NOW());
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trigger_record_openfda_catalog_history ON openfda_catalog;
+CREATE TRIGGER trigger_record_openfda_catalog_history
+    AFTER INSERT OR UPDATE OR DELETE ON openfda_catalog
+    FOR EACH ROW
+    EXECUTE FUNCTION record_openfda_catalog_history();
+
+-- Existing entries become version 1, valid since they were first synced
+INSERT INTO openfda_catalog_history (product_ndc, version, data, valid_from)
+SELECT
+    product_ndc,
+    1,
+    to_jsonb(c) - 'id' - 'last_synced_at' - 'created_at' - 'updated_at' - 'search_vector',
+    COALESCE(created_at, NOW())
+FROM openfda_catalog c
+ON CONFLICT (product_ndc, version) DO NOTHING;
+
+-- Regulatory documents record the catalog version in force when they were generated
+ALTER TABLE regulatory_documents
+ADD COLUMN IF NOT EXISTS catalog_version_id UUID REFERENCES openfda_catalog_history(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_reg_docs_catalog_version ON regulatory_documents(catalog_version_id)
+WHERE catalog_version_id IS NOT NULL;
+
+COMMENT ON TABLE openfda_catalog_history IS 'Versions of OpenFDA NDC directory entries (valid_from/valid_to)';
+COMMENT ON COLUMN regulatory_documents.catalog_version_id IS 'OpenFDA catalog version of the product at generation time';
@@ -0,0 +1,27 @@
+-- Sync Source Settings
+-- Runtime configuration of each catalog sync source, editable by admins without a
+-- redeploy: request rate budget, burst limit, API key and scheduler pause.
+-- NULL rate/burst/key columns fall back to the environment configuration.
+
+CREATE TABLE IF NOT EXISTS sync_source_settings (
+    source VARCHAR(30) PRIMARY KEY,            -- openfda, ema, mhra, health_canada, swissmedic
+    requests_per_minute INTEGER CHECK (requests_per_minute IS NULL OR requests_per_minute > 0),
+    burst_limit INTEGER CHECK (burst_limit IS NULL OR burst_limit > 0),
+    api_key_encrypted TEXT,                    -- AES-256-GCM encrypted
+    scheduler_paused BOOLEAN NOT NULL DEFAULT FALSE,
+    paused_reason TEXT,
+    paused_at TIMESTAMP WITH TIME ZONE,
+    paused_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    updated_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+INSERT INTO sync_source_settings (source)
+VALUES ('openfda'), ('ema'), ('mhra'), ('health_canada'), ('swissmedic')
+ON CONFLICT (source) DO NOTHING;
+
+COMMENT ON TABLE sync_source_settings IS 'Admin-editable rate limits, API keys and scheduler pause per catalog sync source';
+COMMENT ON COLUMN sync_source_settings.requests_per_minute IS 'Request budget shared by all workers of a sync (NULL = environment default)';
+COMMENT ON COLUMN sync_source_settings.burst_limit IS 'Requests an idle budget may send at once (NULL = environment default)';
+COMMENT ON COLUMN sync_source_settings.scheduler_paused IS 'Scheduled syncs are skipped while TRUE; manual syncs still run';
@@ -0,0 +1,20 @@
+-- RxNorm to ATC Mapping
+-- Links RxNorm concepts (RxCUIs on OpenFDA NDC entries) to WHO ATC codes (used by EMA
+-- and the national regulator catalogs), so federated catalog search can group
+-- equivalent products across sources. Loaded from RxNorm's ATC relations (RxClass).
+
+CREATE TABLE IF NOT EXISTS rxnorm_atc_mapping (
+    rxcui VARCHAR(20) NOT NULL,
+    atc_code VARCHAR(10) NOT NULL,
+    ingredient_name TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (rxcui, atc_code)
+);
+
+CREATE INDEX IF NOT EXISTS idx_rxnorm_atc_mapping_atc ON rxnorm_atc_mapping(atc_code);
+
+-- RxCUI containment lookups from OpenFDA entries
+CREATE INDEX IF NOT EXISTS idx_openfda_catalog_rxcui
+ON openfda_catalog USING GIN ((openfda_data->'rxcui'));
+
+COMMENT ON TABLE rxnorm_atc_mapping IS 'RxNorm concept to ATC code mapping for cross-catalog equivalence grouping';
@@ -0,0 +1,68 @@
+-- Flat-File ERP Connector
+-- ERPs without a usable API exchange inventory as CSV files over SFTP. Connections of
+-- type 'flat_file' pull stock files from an inbound directory and push Atlas stock to an
+-- outbound directory on their sync schedule, using the same mappings and sync logs as
+-- the API connectors.
+
+ALTER TABLE erp_connections DROP CONSTRAINT IF EXISTS erp_connections_erp_type_check;
+ALTER TABLE erp_connections ADD CONSTRAINT erp_connections_erp_type_check
+    CHECK (erp_type IN ('netsuite', 'sap_s4hana', 'flat_file'));
+
+ALTER TABLE erp_connections
+ADD COLUMN IF NOT EXISTS sftp_host VARCHAR(255),
+ADD COLUMN IF NOT EXISTS sftp_port INTEGER DEFAULT 22 CHECK (sftp_port BETWEEN 1 AND 65535),
+ADD COLUMN IF NOT EXISTS sftp_username TEXT,                  -- Encrypted
+ADD COLUMN IF NOT EXISTS sftp_password TEXT,                  -- Encrypted
+ADD COLUMN IF NOT EXISTS sftp_private_key TEXT,               -- Encrypted (PEM)
+ADD COLUMN IF NOT EXISTS sftp_host_key_fingerprint VARCHAR(128), -- SHA-256, hex
+ADD COLUMN IF NOT EXISTS sftp_inbound_path VARCHAR(500),
+ADD COLUMN IF NOT EXISTS sftp_outbound_path VARCHAR(500),
+ADD COLUMN IF NOT EXISTS sftp_archive_path VARCHAR(500),
+ADD COLUMN IF NOT EXISTS sftp_file_pattern VARCHAR(100) DEFAULT '*.csv',
+ADD COLUMN IF NOT EXISTS csv_column_mapping JSONB;
+
+ALTER TABLE erp_connections DROP CONSTRAINT IF EXISTS flat_file_fields_required;
+ALTER TABLE erp_connections ADD CONSTRAINT flat_file_fields_required CHECK (
+    (erp_type != 'flat_file') OR
+    (sftp_host IS NOT NULL AND
+     sftp_username IS NOT NULL AND
+     (sftp_password IS NOT NULL OR sftp_private_key IS NOT NULL) AND
+     sftp_inbound_path IS NOT NULL AND
+     sftp_outbound_path IS NOT NULL)
+);
+
+COMMENT ON COLUMN erp_connections.sftp_host_key_fingerprint IS 'Expected SHA-256 host key fingerprint (hex); connections to other hosts are refused';
+COMMENT ON COLUMN erp_connections.csv_column_mapping IS 'CSV column -> Atlas field mapping for flat-file connections';
+
+-- Every file pulled or pushed, with its checksum for duplicate detection
+CREATE TABLE IF NOT EXISTS erp_flat_file_transfers (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    erp_connection_id UUID NOT NULL REFERENCES erp_connections(id) ON DELETE CASCADE,
+    sync_log_id UUID REFERENCES erp_sync_logs(id) ON DELETE SET NULL,
+
+    direction VARCHAR(10) NOT NULL CHECK (direction IN ('inbound', 'outbound')),
+    file_name VARCHAR(500) NOT NULL,
+    file_size BIGINT NOT NULL DEFAULT 0,
+    checksum_sha256 CHAR(64),               -- NULL when the file could not be downloaded
+    status VARCHAR(20) NOT NULL CHECK (status IN ('processed', 'duplicate', 'failed')),
+
+    rows_total INTEGER NOT NULL DEFAULT 0,
+    rows_failed INTEGER NOT NULL DEFAULT 0,
+    error_message TEXT,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- An inbound file's content is processed at most once per connection. Outbound files
+-- may repeat an earlier export when stock returns to a previous state.
+CREATE UNIQUE INDEX IF NOT EXISTS idx_erp_flat_file_transfers_inbound_checksum
+ON erp_flat_file_transfers(erp_connection_id, checksum_sha256)
+WHERE direction = 'inbound' AND status = 'processed';
+
+CREATE INDEX IF NOT EXISTS idx_erp_flat_file_transfers_connection
+ON erp_flat_file_transfers(erp_connection_id, created_at DESC);
+
+-- Due flat-file connections are found by the scheduler
+CREATE INDEX IF NOT EXISTS idx_erp_connections_flat_file_due
+ON erp_connections(last_sync_at)
+WHERE erp_type = 'flat_file' AND sync_enabled = true AND status = 'active';
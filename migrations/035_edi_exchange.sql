@@ -0,0 +1,95 @@
+-- EDI X12 Exchange
+-- Marketplace users whose ERP trades over EDI register a trading partner profile. Atlas
+-- emits an 850 purchase order to the seller's partner when a transaction is created and
+-- receives 855 acknowledgments, 856 ship notices and 810 invoices back over AS2 or SFTP.
+
+CREATE TABLE IF NOT EXISTS edi_trading_partners (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL UNIQUE REFERENCES users(id) ON DELETE CASCADE,
+    name VARCHAR(255) NOT NULL,
+
+    -- X12 envelope identification of the partner
+    isa_qualifier VARCHAR(2) NOT NULL DEFAULT 'ZZ',
+    isa_id VARCHAR(15) NOT NULL,
+    gs_id VARCHAR(15) NOT NULL,
+    x12_version VARCHAR(6) NOT NULL DEFAULT '004010' CHECK (x12_version IN ('004010', '005010')),
+    test_mode BOOLEAN NOT NULL DEFAULT true,
+    last_control_number INTEGER NOT NULL DEFAULT 0 CHECK (last_control_number BETWEEN 0 AND 999999999),
+
+    transport VARCHAR(10) NOT NULL CHECK (transport IN ('as2', 'sftp')),
+
+    -- AS2
+    as2_id VARCHAR(128),
+    as2_url VARCHAR(500),
+    as2_username TEXT,                  -- Encrypted (HTTP basic auth at the partner)
+    as2_password TEXT,                  -- Encrypted
+    as2_inbound_token TEXT,             -- Encrypted (partner authenticates to Atlas with it)
+
+    -- SFTP
+    sftp_host VARCHAR(255),
+    sftp_port INTEGER DEFAULT 22 CHECK (sftp_port BETWEEN 1 AND 65535),
+    sftp_username TEXT,                 -- Encrypted
+    sftp_password TEXT,                 -- Encrypted
+    sftp_private_key TEXT,              -- Encrypted (PEM)
+    sftp_host_key_fingerprint VARCHAR(128),
+    sftp_inbound_path VARCHAR(500),
+    sftp_outbound_path VARCHAR(500),
+    sftp_archive_path VARCHAR(500),
+
+    enabled BOOLEAN NOT NULL DEFAULT true,
+    last_polled_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT edi_as2_fields_required CHECK (
+        transport != 'as2' OR (as2_id IS NOT NULL AND as2_url IS NOT NULL AND as2_inbound_token IS NOT NULL)
+    ),
+    CONSTRAINT edi_sftp_fields_required CHECK (
+        transport != 'sftp' OR
+        (sftp_host IS NOT NULL AND
+         sftp_username IS NOT NULL AND
+         (sftp_password IS NOT NULL OR sftp_private_key IS NOT NULL) AND
+         sftp_inbound_path IS NOT NULL AND
+         sftp_outbound_path IS NOT NULL)
+    )
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_edi_trading_partners_isa
+ON edi_trading_partners(isa_qualifier, isa_id);
+
+COMMENT ON TABLE edi_trading_partners IS 'EDI trading partner profile of a marketplace user (one per user)';
+COMMENT ON COLUMN edi_trading_partners.last_control_number IS 'Last ISA13/GS06 control number issued to the partner';
+
+-- PO numbers are short sequential references (BEG03 allows 22 characters)
+CREATE SEQUENCE IF NOT EXISTS edi_po_number_seq;
+
+-- Every document exchanged, with its raw X12 and the translated content
+CREATE TABLE IF NOT EXISTS edi_documents (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    partner_id UUID NOT NULL REFERENCES edi_trading_partners(id) ON DELETE CASCADE,
+    transaction_id UUID REFERENCES transactions(id) ON DELETE SET NULL,
+
+    direction VARCHAR(10) NOT NULL CHECK (direction IN ('outbound', 'inbound')),
+    transaction_set VARCHAR(3) NOT NULL CHECK (transaction_set IN ('850', '855', '856', '810')),
+    interchange_control_number VARCHAR(9) NOT NULL,
+    transaction_control_number VARCHAR(9) NOT NULL,
+    po_number VARCHAR(22),
+    transport VARCHAR(10) NOT NULL CHECK (transport IN ('as2', 'sftp', 'upload')),
+
+    status VARCHAR(20) NOT NULL CHECK (status IN ('sent', 'failed', 'applied', 'unmatched', 'rejected')),
+    raw_x12 TEXT NOT NULL,
+    translated JSONB NOT NULL DEFAULT '{}'::jsonb,
+    notes TEXT,
+    error_message TEXT,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- A resent interchange (same ISA13 from the same partner) is only applied once
+CREATE UNIQUE INDEX IF NOT EXISTS idx_edi_documents_inbound_control
+ON edi_documents(partner_id, interchange_control_number, transaction_control_number)
+WHERE direction = 'inbound';
+
+CREATE INDEX IF NOT EXISTS idx_edi_documents_partner ON edi_documents(partner_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_edi_documents_transaction ON edi_documents(transaction_id) WHERE transaction_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_edi_documents_po ON edi_documents(partner_id, po_number) WHERE po_number IS NOT NULL;
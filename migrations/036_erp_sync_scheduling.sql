@@ -0,0 +1,20 @@
+-- Scheduled ERP Sync
+-- Every active connection is synced on its own `sync_frequency_minutes`. A connection
+-- runs one sync at a time (claimed via last_sync_status = 'running'), and connections
+-- whose syncs keep failing are retried with exponential backoff.
+
+ALTER TABLE erp_connections
+ADD COLUMN IF NOT EXISTS sync_started_at TIMESTAMPTZ,
+ADD COLUMN IF NOT EXISTS consecutive_failures INTEGER NOT NULL DEFAULT 0,
+ADD COLUMN IF NOT EXISTS next_sync_at TIMESTAMPTZ;
+
+COMMENT ON COLUMN erp_connections.sync_started_at IS 'Start of the running sync; a claim older than 2 hours is treated as abandoned';
+COMMENT ON COLUMN erp_connections.consecutive_failures IS 'Failed syncs in a row (reset on success)';
+COMMENT ON COLUMN erp_connections.next_sync_at IS 'Backoff: earliest time of the next scheduled sync after failures';
+
+-- The scheduler covers all connection types now
+DROP INDEX IF EXISTS idx_erp_connections_flat_file_due;
+
+CREATE INDEX IF NOT EXISTS idx_erp_connections_sync_due
+ON erp_connections(COALESCE(next_sync_at, last_sync_at))
+WHERE sync_enabled = true AND status = 'active';
@@ -0,0 +1,37 @@
+-- Pending ERP Mappings
+-- NetSuite/SAP webhooks report items that have no inventory mapping yet. Those items are
+-- queued here (one row per ERP item and location) until the user maps them to Atlas
+-- inventory or ignores them.
+
+CREATE TABLE IF NOT EXISTS erp_pending_mappings (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    erp_connection_id UUID NOT NULL REFERENCES erp_connections(id) ON DELETE CASCADE,
+
+    -- ERP-side identifiers, as reported by the webhook
+    erp_item_id VARCHAR(100) NOT NULL,
+    erp_location_id VARCHAR(100),
+    erp_item_name VARCHAR(255),
+    ndc_code VARCHAR(50),
+    last_quantity INTEGER,
+    last_event_type VARCHAR(50) NOT NULL,
+    occurrences INTEGER NOT NULL DEFAULT 1,
+
+    -- Resolution
+    status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'mapped', 'ignored')),
+    erp_mapping_id UUID REFERENCES erp_inventory_mappings(id) ON DELETE SET NULL,
+    resolved_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    resolved_at TIMESTAMPTZ,
+
+    first_seen_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_seen_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_erp_pending_mappings_item
+ON erp_pending_mappings(erp_connection_id, erp_item_id, COALESCE(erp_location_id, ''));
+
+CREATE INDEX IF NOT EXISTS idx_erp_pending_mappings_open
+ON erp_pending_mappings(erp_connection_id, last_seen_at DESC)
+WHERE status = 'pending';
+
+COMMENT ON TABLE erp_pending_mappings IS 'ERP items seen in webhooks without an Atlas inventory mapping';
+COMMENT ON COLUMN erp_pending_mappings.occurrences IS 'Webhook events that reported the item while unmapped';
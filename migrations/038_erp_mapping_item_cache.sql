@@ -0,0 +1,10 @@
+-- ERP Item Cache on Mappings
+-- Manually created or edited mappings are validated against the ERP; the fetched item
+-- record is kept on the mapping so the UI can show what an Atlas item is mapped to
+-- without calling the ERP again.
+
+ALTER TABLE erp_inventory_mappings
+ADD COLUMN IF NOT EXISTS erp_item_snapshot JSONB,
+ADD COLUMN IF NOT EXISTS erp_item_fetched_at TIMESTAMPTZ;
+
+COMMENT ON COLUMN erp_inventory_mappings.erp_item_snapshot IS 'ERP item record fetched when the mapping was last created or edited';
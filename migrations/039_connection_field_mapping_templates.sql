@@ -0,0 +1,21 @@
+-- Connection Field Mapping Templates
+-- Each NetSuite/SAP connection uses a field mapping template (which ERP field holds NDC,
+-- lot and expiry) instead of field names hardcoded in the clients. Connections without a
+-- template use the system default of their ERP type. Syncs refuse to run while the
+-- template lacks a field the connection needs.
+
+ALTER TABLE erp_connections
+ADD COLUMN IF NOT EXISTS field_mapping_template_id UUID
+    REFERENCES erp_field_mapping_templates(id) ON DELETE SET NULL;
+
+COMMENT ON COLUMN erp_connections.field_mapping_template_id IS 'Field mapping template; NULL = system default for the ERP type';
+
+-- SAP keeps lots in the standard Batch field
+UPDATE erp_field_mapping_templates
+SET field_mappings = field_mappings || '{"lot_number": "Batch"}'::jsonb, updated_at = NOW()
+WHERE erp_type = 'sap_s4hana' AND template_name = 'Default Pharmaceutical' AND created_by IS NULL
+  AND NOT field_mappings ? 'lot_number';
+
+CREATE INDEX IF NOT EXISTS idx_field_templates_owner
+ON erp_field_mapping_templates(created_by)
+WHERE created_by IS NOT NULL;
@@ -0,0 +1,33 @@
+-- ERP Conflict Review
+-- Conflicts queued under manual conflict resolution can be resolved from the review
+-- queue: the chosen side is written to Atlas and/or the ERP, and the resolution can be
+-- undone within a short window using the values it replaced.
+
+ALTER TABLE erp_conflict_queue
+ADD COLUMN IF NOT EXISTS applied_values JSONB,
+ADD COLUMN IF NOT EXISTS undo_expires_at TIMESTAMPTZ,
+ADD COLUMN IF NOT EXISTS ai_resolution_id UUID REFERENCES erp_ai_conflict_resolutions(id) ON DELETE SET NULL;
+
+COMMENT ON COLUMN erp_conflict_queue.applied_values IS 'Quantities before and after the resolution was applied, for undo';
+COMMENT ON COLUMN erp_conflict_queue.undo_expires_at IS 'The resolution can be undone until this time';
+COMMENT ON COLUMN erp_conflict_queue.ai_resolution_id IS 'AI suggestion marked as acted on by the resolution';
+
+-- Repeated syncs refresh the open conflict of a mapping instead of queueing duplicates
+UPDATE erp_conflict_queue q
+SET status = 'ignored', resolution_notes = 'Superseded by a newer conflict for the same mapping'
+WHERE status = 'pending'
+  AND EXISTS (
+      SELECT 1 FROM erp_conflict_queue n
+      WHERE n.erp_mapping_id = q.erp_mapping_id
+        AND n.conflict_type = q.conflict_type
+        AND n.status = 'pending'
+        AND (n.detected_at, n.id) > (q.detected_at, q.id)
+  );
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_conflict_queue_open_mapping
+ON erp_conflict_queue(erp_mapping_id, conflict_type)
+WHERE status = 'pending';
+
+CREATE INDEX IF NOT EXISTS idx_erp_ai_conflict_open_item
+ON erp_ai_conflict_resolutions(erp_connection_id, atlas_inventory_id, erp_item_id, created_at DESC)
+WHERE resolved_at IS NULL;
@@ -0,0 +1,41 @@
+-- ERP Sandbox Mode
+-- A connection in sandbox mode reads from the ERP as usual, but every write to the ERP
+-- (quantity updates, custom fields, goods movements, flat-file uploads) is captured here
+-- instead of executed, so the would-be changes can be reviewed as a diff before real
+-- syncing is enabled.
+
+ALTER TABLE erp_connections
+ADD COLUMN IF NOT EXISTS sandbox_mode BOOLEAN NOT NULL DEFAULT false;
+
+ALTER TABLE erp_sync_logs
+ADD COLUMN IF NOT EXISTS sandbox BOOLEAN NOT NULL DEFAULT false;
+
+COMMENT ON COLUMN erp_connections.sandbox_mode IS 'Capture ERP writes in erp_sandbox_writes instead of executing them';
+COMMENT ON COLUMN erp_sync_logs.sandbox IS 'Sync ran in sandbox mode; no ERP writes were executed';
+
+CREATE TABLE IF NOT EXISTS erp_sandbox_writes (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    erp_connection_id UUID NOT NULL REFERENCES erp_connections(id) ON DELETE CASCADE,
+    erp_sync_log_id UUID REFERENCES erp_sync_logs(id) ON DELETE CASCADE,
+    erp_mapping_id UUID REFERENCES erp_inventory_mappings(id) ON DELETE SET NULL,
+
+    -- Target of the write (item ID, or file name for flat-file uploads)
+    erp_item_id VARCHAR(255) NOT NULL,
+    erp_location_id VARCHAR(100),
+    operation VARCHAR(50) NOT NULL CHECK (operation IN ('update_quantity', 'update_custom_fields', 'goods_movement', 'file_upload')),
+
+    -- Diff: value in the ERP when the write was captured, and the value Atlas would write
+    current_value JSONB,
+    proposed_value JSONB NOT NULL,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_erp_sandbox_writes_connection
+ON erp_sandbox_writes(erp_connection_id, created_at DESC);
+
+CREATE INDEX IF NOT EXISTS idx_erp_sandbox_writes_sync_log
+ON erp_sandbox_writes(erp_sync_log_id)
+WHERE erp_sync_log_id IS NOT NULL;
+
+COMMENT ON TABLE erp_sandbox_writes IS 'ERP writes captured (not executed) for connections in sandbox mode';
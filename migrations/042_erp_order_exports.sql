@@ -0,0 +1,53 @@
+-- ERP Order Exports
+-- Completed marketplace transactions are exported to the parties' ERPs: a sales order in
+-- the seller's ERP and a purchase order in the buyer's. Each export is recorded once per
+-- transaction and side; its ID doubles as the idempotency key sent to the ERP, so retries
+-- never create a second document. The ERP document number is written back onto the
+-- transaction.
+
+ALTER TABLE erp_connections
+ADD COLUMN IF NOT EXISTS export_sales_orders BOOLEAN NOT NULL DEFAULT false,
+ADD COLUMN IF NOT EXISTS export_purchase_orders BOOLEAN NOT NULL DEFAULT false,
+ADD COLUMN IF NOT EXISTS order_customer_id VARCHAR(100),
+ADD COLUMN IF NOT EXISTS order_vendor_id VARCHAR(100);
+
+COMMENT ON COLUMN erp_connections.export_sales_orders IS 'Create ERP sales orders for completed sales (requires sync_transactions)';
+COMMENT ON COLUMN erp_connections.export_purchase_orders IS 'Create ERP purchase orders for completed purchases (requires sync_transactions)';
+COMMENT ON COLUMN erp_connections.order_customer_id IS 'ERP customer that marketplace sales orders are booked against';
+COMMENT ON COLUMN erp_connections.order_vendor_id IS 'ERP vendor/supplier that marketplace purchase orders are booked against';
+
+ALTER TABLE transactions
+ADD COLUMN IF NOT EXISTS erp_sales_order_number VARCHAR(100),
+ADD COLUMN IF NOT EXISTS erp_purchase_order_number VARCHAR(100);
+
+CREATE TABLE IF NOT EXISTS erp_order_exports (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    transaction_id UUID NOT NULL REFERENCES transactions(id) ON DELETE CASCADE,
+    erp_connection_id UUID NOT NULL REFERENCES erp_connections(id) ON DELETE CASCADE,
+    order_type VARCHAR(20) NOT NULL CHECK (order_type IN ('sales_order', 'purchase_order')),
+
+    status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'created', 'failed', 'sandbox')),
+    erp_document_id VARCHAR(100),
+    erp_document_number VARCHAR(100),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    error_message TEXT,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    UNIQUE (transaction_id, order_type)
+);
+
+CREATE INDEX IF NOT EXISTS idx_erp_order_exports_connection
+ON erp_order_exports(erp_connection_id, created_at DESC);
+
+COMMENT ON TABLE erp_order_exports IS 'Sales/purchase orders created in ERPs for marketplace transactions';
+COMMENT ON COLUMN erp_order_exports.id IS 'Also the idempotency key sent to the ERP';
+
+-- Sandbox connections capture order creation too
+ALTER TABLE erp_sandbox_writes DROP CONSTRAINT IF EXISTS erp_sandbox_writes_operation_check;
+ALTER TABLE erp_sandbox_writes ADD CONSTRAINT erp_sandbox_writes_operation_check
+CHECK (operation IN (
+    'update_quantity', 'update_custom_fields', 'goods_movement', 'file_upload',
+    'create_sales_order', 'create_purchase_order'
+));
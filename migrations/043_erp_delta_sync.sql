@@ -0,0 +1,12 @@
+-- ERP Delta Sync
+-- ERP -> Atlas syncs only fetch what changed since the previous sync. The cursor is
+-- persisted per connection: for NetSuite the lastModifiedDate the next saved search
+-- filters on, for SAP the OData delta token. A missing, stale or rejected cursor falls
+-- back to a full sync, which issues a fresh one.
+
+ALTER TABLE erp_connections
+ADD COLUMN IF NOT EXISTS delta_token TEXT,
+ADD COLUMN IF NOT EXISTS delta_token_updated_at TIMESTAMPTZ;
+
+COMMENT ON COLUMN erp_connections.delta_token IS 'ERP change cursor: NetSuite lastModifiedDate (RFC 3339) or SAP OData delta token';
+COMMENT ON COLUMN erp_connections.delta_token_updated_at IS 'When the delta token was issued; stale tokens force a full sync';
@@ -0,0 +1,28 @@
+-- ERP Item Catalog Cache
+-- Items fetched from a connection's ERP are kept locally so the mapping UI can search
+-- them instantly and AI mapping discovery does not refetch the catalog on every run.
+-- Each refresh replaces the connection's catalog; fetched_at tells how fresh it is.
+
+CREATE TABLE IF NOT EXISTS erp_item_catalog (
+    erp_connection_id UUID NOT NULL REFERENCES erp_connections(id) ON DELETE CASCADE,
+    erp_item_id VARCHAR(255) NOT NULL,
+    name VARCHAR(500) NOT NULL,
+    description TEXT,
+    quantity DOUBLE PRECISION NOT NULL DEFAULT 0,
+    custom_fields JSONB NOT NULL DEFAULT '{}'::jsonb,
+    fetched_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (erp_connection_id, erp_item_id)
+);
+
+-- Browse/search by name within a connection
+CREATE INDEX IF NOT EXISTS idx_erp_item_catalog_name
+ON erp_item_catalog (erp_connection_id, lower(name));
+
+-- NDC lookups from the cached custom fields
+CREATE INDEX IF NOT EXISTS idx_erp_item_catalog_ndc
+ON erp_item_catalog (erp_connection_id, (custom_fields->>'ndc_code'))
+WHERE custom_fields ? 'ndc_code';
+
+COMMENT ON TABLE erp_item_catalog IS 'Locally cached ERP items per connection, replaced on each catalog refresh';
+COMMENT ON COLUMN erp_item_catalog.custom_fields IS 'Normalized item attributes: ndc_code, lot_number, expiry_date, manufacturer, ...';
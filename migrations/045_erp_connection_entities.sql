@@ -0,0 +1,49 @@
+-- ERP Connection Entities
+-- One connection can sync several NetSuite subsidiaries or SAP plants. Each entity maps
+-- onto an Atlas storage location: inventory stored there is synced against that entity,
+-- and ERP stock read from the entity is matched against mappings tagged with it.
+-- A connection without entities keeps using its single sap_plant / default location.
+
+CREATE TABLE IF NOT EXISTS erp_connection_entities (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    erp_connection_id UUID NOT NULL REFERENCES erp_connections(id) ON DELETE CASCADE,
+    entity_type VARCHAR(20) NOT NULL CHECK (entity_type IN ('subsidiary', 'plant')),
+    entity_id VARCHAR(100) NOT NULL,        -- NetSuite subsidiary internal ID or SAP plant
+    entity_name VARCHAR(255),
+    company_code VARCHAR(10),               -- SAP company code of the plant
+    default_location_id VARCHAR(100),       -- NetSuite location used for new mappings
+    atlas_storage_location VARCHAR(100),    -- inventory.storage_location served by this entity
+    sync_enabled BOOLEAN NOT NULL DEFAULT true,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (erp_connection_id, entity_id)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_erp_connection_entities_storage_location
+    ON erp_connection_entities(erp_connection_id, atlas_storage_location)
+    WHERE atlas_storage_location IS NOT NULL;
+
+-- Which entity a mapping syncs against (NULL: the connection default)
+ALTER TABLE erp_inventory_mappings
+ADD COLUMN IF NOT EXISTS erp_entity_id VARCHAR(100);
+
+CREATE INDEX IF NOT EXISTS idx_erp_mappings_entity
+    ON erp_inventory_mappings(erp_connection_id, erp_entity_id);
+
+-- Entities a sync run covered (NULL: the connection default)
+ALTER TABLE erp_sync_logs
+ADD COLUMN IF NOT EXISTS entity_ids TEXT[];
+
+CREATE INDEX IF NOT EXISTS idx_erp_sync_logs_entity_ids
+    ON erp_sync_logs USING GIN (entity_ids);
+
+-- Existing SAP connections: their configured plant becomes the first entity
+INSERT INTO erp_connection_entities (erp_connection_id, entity_type, entity_id, company_code)
+SELECT id, 'plant', sap_plant, sap_company_code
+FROM erp_connections
+WHERE erp_type = 'sap_s4hana' AND sap_plant IS NOT NULL
+ON CONFLICT (erp_connection_id, entity_id) DO NOTHING;
+
+COMMENT ON TABLE erp_connection_entities IS 'NetSuite subsidiaries / SAP plants synced by an ERP connection';
+COMMENT ON COLUMN erp_inventory_mappings.erp_entity_id IS 'Subsidiary or plant the mapping syncs against';
+COMMENT ON COLUMN erp_sync_logs.entity_ids IS 'Subsidiaries or plants covered by the sync run';
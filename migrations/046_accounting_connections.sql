@@ -0,0 +1,69 @@
+-- Accounting Connections (QuickBooks Online / Xero)
+-- Lightweight connectors, separate from the ERP inventory sync: completed marketplace
+-- sales are pushed to the seller's accounting system as an invoice to the buyer and,
+-- optionally, a payment applied to it. Authorization is OAuth2 (authorization code);
+-- tokens are stored encrypted and refreshed on use.
+
+CREATE TABLE IF NOT EXISTS accounting_connections (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    provider VARCHAR(20) NOT NULL CHECK (provider IN ('quickbooks', 'xero')),
+    connection_name VARCHAR(255) NOT NULL,
+    status VARCHAR(30) NOT NULL DEFAULT 'pending_authorization'
+        CHECK (status IN ('pending_authorization', 'active', 'error', 'disabled')),
+    sandbox BOOLEAN NOT NULL DEFAULT false,   -- QuickBooks sandbox company
+
+    -- OAuth2 app (encrypted)
+    client_id TEXT NOT NULL,
+    client_secret TEXT NOT NULL,
+
+    -- Authorization in progress
+    oauth_state_hash VARCHAR(64),
+    oauth_state_expires_at TIMESTAMPTZ,
+
+    -- Tokens (encrypted) and the authorized company: QuickBooks realm ID / Xero tenant ID
+    access_token TEXT,
+    refresh_token TEXT,
+    token_expires_at TIMESTAMPTZ,
+    tenant_id VARCHAR(100),
+
+    -- Export settings
+    export_invoices BOOLEAN NOT NULL DEFAULT true,
+    record_payments BOOLEAN NOT NULL DEFAULT false,
+    customer_ref VARCHAR(100),          -- Customer (QuickBooks) / contact (Xero) invoices are billed to
+    item_ref VARCHAR(100),              -- QuickBooks item ID / Xero sales account code
+    payment_account_ref VARCHAR(100),   -- QuickBooks deposit account ID / Xero bank account code
+
+    last_error TEXT,
+    last_export_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_accounting_connections_user ON accounting_connections(user_id);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_accounting_connections_state
+    ON accounting_connections(oauth_state_hash) WHERE oauth_state_hash IS NOT NULL;
+
+CREATE TABLE IF NOT EXISTS accounting_exports (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    accounting_connection_id UUID NOT NULL REFERENCES accounting_connections(id) ON DELETE CASCADE,
+    transaction_id UUID NOT NULL REFERENCES transactions(id) ON DELETE CASCADE,
+
+    status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'invoiced', 'paid', 'failed')),
+    invoice_id VARCHAR(100),
+    invoice_number VARCHAR(100),
+    payment_id VARCHAR(100),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    error_message TEXT,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    UNIQUE (accounting_connection_id, transaction_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_accounting_exports_transaction ON accounting_exports(transaction_id);
+
+COMMENT ON TABLE accounting_connections IS 'QuickBooks Online / Xero connections receiving marketplace invoices';
+COMMENT ON TABLE accounting_exports IS 'Invoices (and payments) pushed to accounting systems per transaction';
+COMMENT ON COLUMN accounting_exports.id IS 'Also the idempotency key of the invoice request; payments use a key derived from it';
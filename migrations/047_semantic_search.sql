@@ -0,0 +1,64 @@
+-- Semantic Search
+-- Embeddings of pharmaceuticals, marketplace inventory and the OpenFDA / EMA catalogs,
+-- searched together with full-text ranking by GET /api/search/semantic. Background
+-- backfill jobs embed new and changed records (tracked by content hash) and drop the
+-- embeddings of deleted ones; the IVFFlat index is rebuilt as the table grows.
+
+CREATE TABLE IF NOT EXISTS search_embeddings (
+    entity_type VARCHAR(20) NOT NULL CHECK (entity_type IN ('pharmaceutical', 'inventory', 'openfda', 'ema')),
+    entity_id UUID NOT NULL,
+    title TEXT NOT NULL,
+    subtitle TEXT,
+    content_hash VARCHAR(64) NOT NULL,
+    embedding VECTOR(1536) NOT NULL,
+    embedded_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (entity_type, entity_id)
+);
+
+-- Rebuilt with lists ~ sqrt(rows) by index maintenance
+CREATE INDEX IF NOT EXISTS idx_search_embeddings_vector ON search_embeddings
+    USING ivfflat (embedding vector_cosine_ops) WITH (lists = 100);
+
+COMMENT ON TABLE search_embeddings IS 'Vector embeddings of catalog and inventory records for semantic search';
+COMMENT ON COLUMN search_embeddings.content_hash IS 'SHA-256 of the embedded text; a different hash means the record changed';
+
+-- Full-text ranking for the Atlas pharmaceutical catalog (OpenFDA and EMA already have one)
+ALTER TABLE pharmaceuticals ADD COLUMN IF NOT EXISTS search_vector tsvector GENERATED ALWAYS AS (
+    setweight(to_tsvector('english', coalesce(brand_name, '')), 'A') ||
+    setweight(to_tsvector('english', coalesce(generic_name, '')), 'A') ||
+    setweight(to_tsvector('english', coalesce(manufacturer, '')), 'B') ||
+    setweight(to_tsvector('english', coalesce(category, '')), 'C') ||
+    setweight(to_tsvector('english', coalesce(description, '')), 'D')
+) STORED;
+
+CREATE INDEX IF NOT EXISTS idx_pharma_search_vector ON pharmaceuticals USING gin(search_vector);
+
+-- ============================================================================
+-- Backfill Jobs
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS search_embedding_jobs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    entity_types TEXT[] NOT NULL,
+    -- Full jobs re-embed every record; incremental jobs only new and changed ones
+    full_rebuild BOOLEAN NOT NULL DEFAULT false,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'running', 'completed', 'failed')),
+    triggered_by VARCHAR(20) NOT NULL DEFAULT 'manual',
+    records_scanned INTEGER NOT NULL DEFAULT 0,
+    records_embedded INTEGER NOT NULL DEFAULT 0,
+    records_removed INTEGER NOT NULL DEFAULT 0,
+    error_message TEXT,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    started_at TIMESTAMPTZ,
+    completed_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_search_embedding_jobs_created ON search_embedding_jobs (created_at DESC);
+
+-- One job at a time
+CREATE UNIQUE INDEX IF NOT EXISTS idx_search_embedding_jobs_active
+ON search_embedding_jobs ((true)) WHERE status IN ('pending', 'running');
+
+COMMENT ON TABLE search_embedding_jobs IS 'Semantic search embedding backfill runs (admin-triggered or scheduled)';
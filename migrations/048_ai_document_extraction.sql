@@ -0,0 +1,49 @@
+-- AI Document Extraction
+-- PDFs and images (Certificates of Analysis, supplier price lists, packing lists) uploaded
+-- to AI import are read by Claude, including scanned pages. Every extracted field carries
+-- a confidence; low-confidence fields are confirmed or corrected by a reviewer before the
+-- document is applied to inventory or attached to a marketplace transaction.
+
+CREATE TABLE IF NOT EXISTS ai_document_extractions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    session_id UUID NOT NULL UNIQUE REFERENCES ai_import_sessions(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id),
+
+    document_kind VARCHAR(20) NOT NULL CHECK (document_kind IN ('coa', 'price_list', 'packing_list')),
+    media_type VARCHAR(50) NOT NULL,
+    -- Header fields, line items and warnings, each field as {value, confidence, reviewed}
+    extraction JSONB NOT NULL,
+
+    status VARCHAR(20) NOT NULL DEFAULT 'pending_review' CHECK (status IN ('pending_review', 'applied')),
+    target VARCHAR(20) CHECK (target IN ('inventory', 'transaction')),
+    transaction_id UUID REFERENCES transactions(id),
+    -- Inventory lots a COA was linked to
+    linked_inventory_ids UUID[] NOT NULL DEFAULT '{}',
+
+    reviewed_by UUID REFERENCES users(id),
+    reviewed_at TIMESTAMPTZ,
+    applied_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_ai_document_extractions_user ON ai_document_extractions(user_id, created_at DESC);
+
+COMMENT ON TABLE ai_document_extractions IS 'Structured fields extracted from uploaded PDFs and images, with per-field confidence for review';
+
+-- Documents attached to marketplace transactions (COAs, packing lists, price lists)
+CREATE TABLE IF NOT EXISTS transaction_documents (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    transaction_id UUID NOT NULL REFERENCES transactions(id) ON DELETE CASCADE,
+    extraction_id UUID NOT NULL UNIQUE REFERENCES ai_document_extractions(id) ON DELETE CASCADE,
+    document_kind VARCHAR(20) NOT NULL,
+    original_filename VARCHAR(500) NOT NULL,
+    -- Reviewed header fields and line items as applied
+    fields JSONB NOT NULL,
+    attached_by UUID NOT NULL REFERENCES users(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_transaction_documents_transaction ON transaction_documents(transaction_id);
+
+COMMENT ON TABLE transaction_documents IS 'Reviewed document extractions attached to a marketplace transaction';
@@ -0,0 +1,72 @@
+-- NL Query Reports
+-- Saved favorites can be scheduled to run daily or weekly. Each run executes the query
+-- again (counting against the monthly NL query quota), stores the result as an encrypted
+-- CSV/XLSX file and delivers it through the notification center and, when enabled, email.
+
+CREATE TABLE IF NOT EXISTS nl_query_report_schedules (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    favorite_id UUID NOT NULL REFERENCES nl_query_favorites(id) ON DELETE CASCADE,
+    name VARCHAR(255) NOT NULL CHECK (length(name) > 0),
+
+    frequency VARCHAR(10) NOT NULL CHECK (frequency IN ('daily', 'weekly')),
+    -- 0 = Monday .. 6 = Sunday, weekly schedules only
+    day_of_week SMALLINT CHECK (day_of_week BETWEEN 0 AND 6),
+    hour_utc SMALLINT NOT NULL DEFAULT 6 CHECK (hour_utc BETWEEN 0 AND 23),
+    format VARCHAR(10) NOT NULL DEFAULT 'csv' CHECK (format IN ('csv', 'xlsx')),
+    email_delivery BOOLEAN NOT NULL DEFAULT false,
+
+    is_active BOOLEAN NOT NULL DEFAULT true,
+    next_run_at TIMESTAMPTZ NOT NULL,
+    last_run_at TIMESTAMPTZ,
+    last_status VARCHAR(20),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CHECK (frequency = 'daily' OR day_of_week IS NOT NULL)
+);
+
+CREATE INDEX IF NOT EXISTS idx_nl_report_schedules_user ON nl_query_report_schedules(user_id);
+CREATE INDEX IF NOT EXISTS idx_nl_report_schedules_due ON nl_query_report_schedules(next_run_at) WHERE is_active;
+
+COMMENT ON TABLE nl_query_report_schedules IS 'Daily/weekly runs of a saved NL query, delivered as CSV or XLSX reports';
+
+-- Every generated (or skipped) report, scheduled or exported on demand
+CREATE TABLE IF NOT EXISTS nl_query_report_files (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    schedule_id UUID REFERENCES nl_query_report_schedules(id) ON DELETE SET NULL,
+    session_id UUID REFERENCES nl_query_sessions(id) ON DELETE SET NULL,
+
+    status VARCHAR(20) NOT NULL CHECK (status IN ('generated', 'skipped_quota', 'failed')),
+    format VARCHAR(10) NOT NULL CHECK (format IN ('csv', 'xlsx')),
+    filename VARCHAR(500),
+    -- Encrypted file relative to FILE_STORAGE_PATH
+    file_path VARCHAR(1000),
+    file_size_bytes BIGINT,
+    row_count INTEGER,
+    emailed BOOLEAN NOT NULL DEFAULT false,
+    error_message TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_nl_report_files_user ON nl_query_report_files(user_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_nl_report_files_schedule ON nl_query_report_files(schedule_id, created_at DESC);
+
+COMMENT ON TABLE nl_query_report_files IS 'History of NL query report files (generated, or skipped for lack of quota)';
+
+-- Allow report notifications in the notification center
+ALTER TABLE alert_notifications DROP CONSTRAINT IF EXISTS alert_notifications_alert_type_check;
+ALTER TABLE alert_notifications ADD CONSTRAINT alert_notifications_alert_type_check
+CHECK (alert_type IN (
+    'expiry_warning',
+    'expiry_critical',
+    'low_stock',
+    'watchlist_match',
+    'price_drop',
+    'new_inquiry',
+    'inquiry_message',
+    'catalog_change',
+    'report_ready',
+    'system'
+));
@@ -0,0 +1,39 @@
+-- AI Pricing Suggestions
+-- Listing price suggestions for an NDC, expiry window and quantity. The market snapshot
+-- (marketplace listings and transaction history, recalls, FDA listing and EMA
+-- authorization status) is stored with each suggestion so its rationale can be audited.
+
+CREATE TABLE IF NOT EXISTS ai_pricing_suggestions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+
+    ndc_code VARCHAR(20) NOT NULL,
+    expiry_from DATE NOT NULL,
+    expiry_to DATE NOT NULL,
+    quantity INTEGER NOT NULL CHECK (quantity > 0),
+
+    suggested_unit_price DECIMAL(12, 2) NOT NULL CHECK (suggested_unit_price >= 0),
+    price_range_low DECIMAL(12, 2),
+    price_range_high DECIMAL(12, 2),
+    confidence DECIMAL(4, 3) NOT NULL CHECK (confidence BETWEEN 0 AND 1),
+    rationale TEXT NOT NULL,
+    market_snapshot JSONB NOT NULL,
+
+    ai_cost_usd DECIMAL(10, 6) NOT NULL DEFAULT 0,
+    ai_tokens_used INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CHECK (expiry_to >= expiry_from)
+);
+
+CREATE INDEX IF NOT EXISTS idx_ai_pricing_suggestions_user ON ai_pricing_suggestions(user_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_ai_pricing_suggestions_ndc ON ai_pricing_suggestions(ndc_code);
+
+COMMENT ON TABLE ai_pricing_suggestions IS 'AI listing price suggestions with the market data they were based on';
+
+ALTER TABLE user_ai_usage_limits
+ADD COLUMN IF NOT EXISTS monthly_pricing_suggestion_limit INTEGER NOT NULL DEFAULT 100,
+ADD COLUMN IF NOT EXISTS monthly_pricing_suggestions_used INTEGER NOT NULL DEFAULT 0;
+
+COMMENT ON COLUMN user_ai_usage_limits.monthly_pricing_suggestion_limit IS 'Maximum AI pricing suggestions per month';
+COMMENT ON COLUMN user_ai_usage_limits.monthly_pricing_suggestions_used IS 'AI pricing suggestions used this period';
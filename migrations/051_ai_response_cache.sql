@@ -0,0 +1,26 @@
+-- AI Response Cache
+-- Validated Claude responses keyed by a SHA-256 of the full request (model, system prompt,
+-- messages, sampling settings), so identical requests don't re-spend quota or API cost.
+-- Entries are scoped to the requesting user and expire after a per-feature TTL.
+
+CREATE TABLE IF NOT EXISTS ai_response_cache (
+    cache_key VARCHAR(64) PRIMARY KEY,
+    feature VARCHAR(50) NOT NULL,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+
+    content TEXT NOT NULL,
+    input_tokens INTEGER NOT NULL DEFAULT 0,
+    output_tokens INTEGER NOT NULL DEFAULT 0,
+    cost_usd DECIMAL(10, 6) NOT NULL DEFAULT 0,
+
+    hit_count INTEGER NOT NULL DEFAULT 0,
+    last_hit_at TIMESTAMPTZ,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_ai_response_cache_expires ON ai_response_cache(expires_at);
+CREATE INDEX IF NOT EXISTS idx_ai_response_cache_feature ON ai_response_cache(feature);
+
+COMMENT ON TABLE ai_response_cache IS 'Validated AI responses reused for identical requests';
+COMMENT ON COLUMN ai_response_cache.cost_usd IS 'Cost of the original API call; each hit saves this amount';
@@ -0,0 +1,89 @@
+-- AI Quota Tiers, Overrides and Top-ups
+-- Plan tiers set each user's monthly AI limits per feature. Admin overrides replace a
+-- tier limit for one user and feature; top-ups add to it for the current period only.
+-- The effective limits are written to the existing user_ai_usage_limits columns, so the
+-- quota checks in each AI service stay unchanged.
+
+CREATE TABLE IF NOT EXISTS ai_quota_tiers (
+    tier VARCHAR(20) PRIMARY KEY,
+    display_name VARCHAR(50) NOT NULL,
+    monthly_price_usd DECIMAL(10, 2) NOT NULL DEFAULT 0,
+
+    monthly_import_limit INTEGER NOT NULL,
+    monthly_nl_query_limit INTEGER NOT NULL,
+    monthly_inquiry_assist_limit INTEGER NOT NULL,
+    monthly_pricing_suggestion_limit INTEGER NOT NULL,
+    monthly_erp_ai_mapping_limit INTEGER NOT NULL,
+    monthly_erp_ai_analysis_limit INTEGER NOT NULL,
+    monthly_erp_ai_conflict_limit INTEGER NOT NULL,
+    monthly_ai_cost_limit_usd DECIMAL(10, 2) NOT NULL,
+
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Free matches the previous flat defaults
+INSERT INTO ai_quota_tiers (
+    tier, display_name, monthly_price_usd,
+    monthly_import_limit, monthly_nl_query_limit, monthly_inquiry_assist_limit, monthly_pricing_suggestion_limit,
+    monthly_erp_ai_mapping_limit, monthly_erp_ai_analysis_limit, monthly_erp_ai_conflict_limit,
+    monthly_ai_cost_limit_usd
+) VALUES
+    ('free', 'Free', 0, 50, 100, 200, 100, 5, 50, 20, 10.00),
+    ('pro', 'Pro', 199.00, 500, 1000, 2000, 1000, 50, 500, 200, 100.00),
+    ('enterprise', 'Enterprise', 999.00, 5000, 10000, 20000, 10000, 500, 5000, 2000, 1000.00)
+ON CONFLICT (tier) DO NOTHING;
+
+ALTER TABLE user_ai_usage_limits
+ADD COLUMN IF NOT EXISTS plan_tier VARCHAR(20) NOT NULL DEFAULT 'free' REFERENCES ai_quota_tiers(tier);
+
+COMMENT ON COLUMN user_ai_usage_limits.plan_tier IS 'AI plan tier the monthly limits are derived from';
+
+-- Per-user limit overrides set by admins (replace the tier limit for one feature)
+CREATE TABLE IF NOT EXISTS ai_quota_overrides (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    feature VARCHAR(30) NOT NULL,
+    monthly_limit DECIMAL(12, 2) NOT NULL CHECK (monthly_limit >= 0),
+    reason TEXT,
+    set_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    expires_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, feature)
+);
+
+-- Quota purchased for the current period
+CREATE TABLE IF NOT EXISTS ai_quota_topups (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    feature VARCHAR(30) NOT NULL,
+    amount DECIMAL(12, 2) NOT NULL CHECK (amount > 0),
+    price_usd DECIMAL(10, 2) NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'completed' CHECK (status IN ('pending', 'completed', 'refunded')),
+    payment_reference TEXT,
+    period_end DATE NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_ai_quota_topups_user_period ON ai_quota_topups(user_id, period_end);
+
+COMMENT ON TABLE ai_quota_topups IS 'AI quota top-ups; they only count towards the period they were bought in';
+
+-- Reset every feature counter, not only imports and cost
+CREATE OR REPLACE FUNCTION reset_monthly_ai_limits()
+RETURNS void AS $$
+BEGIN
+    UPDATE user_ai_usage_limits
+    SET
+        monthly_imports_used = 0,
+        monthly_nl_queries_used = 0,
+        monthly_inquiry_assists_used = 0,
+        monthly_pricing_suggestions_used = 0,
+        monthly_erp_ai_mapping_used = 0,
+        monthly_erp_ai_analysis_used = 0,
+        monthly_erp_ai_conflict_used = 0,
+        monthly_ai_cost_used_usd = 0.00,
+        limit_period_start = CURRENT_DATE,
+        limit_period_end = CURRENT_DATE + INTERVAL '1 month',
+        updated_at = NOW()
+    WHERE limit_period_end <= CURRENT_DATE;
+END;
+$$ LANGUAGE plpgsql;
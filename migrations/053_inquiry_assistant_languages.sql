@@ -0,0 +1,25 @@
+-- Multilingual Inquiry Assistant
+-- Suggestions are written in the counterparty's language (from their language
+-- preference, else detected from their messages) with an English translation attached.
+
+CREATE TABLE IF NOT EXISTS user_language_preferences (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    -- Language the user writes in; used when they are the counterparty (ISO 639-1)
+    preferred_language VARCHAR(10),
+    -- Language for suggestions when the counterparty's language is unknown (ISO 639-1)
+    negotiation_language VARCHAR(10) NOT NULL DEFAULT 'en',
+    include_translation BOOLEAN NOT NULL DEFAULT TRUE,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON TABLE user_language_preferences IS 'Per-user languages for marketplace communication and the inquiry assistant';
+
+ALTER TABLE inquiry_ai_suggestions
+ADD COLUMN IF NOT EXISTS language VARCHAR(10) NOT NULL DEFAULT 'en',
+ADD COLUMN IF NOT EXISTS language_source VARCHAR(20) NOT NULL DEFAULT 'default'
+    CHECK (language_source IN ('request', 'profile', 'message_history', 'preference', 'default')),
+ADD COLUMN IF NOT EXISTS english_translation TEXT;
+
+COMMENT ON COLUMN inquiry_ai_suggestions.language IS 'ISO 639-1 language the suggestion is written in';
+COMMENT ON COLUMN inquiry_ai_suggestions.language_source IS 'How the language was chosen';
+COMMENT ON COLUMN inquiry_ai_suggestions.english_translation IS 'English translation for the seller when the suggestion is not in English';
@@ -0,0 +1,40 @@
+-- AI Import Column-Mapping Memory
+-- A confirmed column mapping is remembered per user under a fingerprint of the file's
+-- header row, so repeat uploads with the same structure (e.g. a supplier's weekly price
+-- list) skip the AI analysis. A changed header row falls back to a fresh analysis.
+
+CREATE TABLE IF NOT EXISTS ai_import_mapping_memory (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+
+    -- SHA256 of the sorted, trimmed header names
+    header_fingerprint VARCHAR(64) NOT NULL,
+    headers JSONB NOT NULL,
+    supplier_name VARCHAR(255),
+
+    -- Mapping as confirmed by the user at import time
+    mapping JSONB NOT NULL,
+    confidence_scores JSONB,
+
+    -- Usage
+    times_reused INTEGER NOT NULL DEFAULT 0,
+    last_session_id UUID REFERENCES ai_import_sessions(id) ON DELETE SET NULL,
+    confirmed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    UNIQUE (user_id, header_fingerprint)
+);
+
+CREATE INDEX idx_ai_import_mapping_memory_user ON ai_import_mapping_memory(user_id, confirmed_at DESC);
+
+COMMENT ON TABLE ai_import_mapping_memory IS 'Confirmed AI import column mappings, reused for files with the same header row';
+
+ALTER TABLE ai_import_sessions
+ADD COLUMN IF NOT EXISTS header_fingerprint VARCHAR(64),
+ADD COLUMN IF NOT EXISTS supplier_name VARCHAR(255),
+ADD COLUMN IF NOT EXISTS mapping_source VARCHAR(20)
+    CHECK (mapping_source IN ('ai', 'memory')),
+ADD COLUMN IF NOT EXISTS mapping_memory_id UUID REFERENCES ai_import_mapping_memory(id) ON DELETE SET NULL;
+
+COMMENT ON COLUMN ai_import_sessions.mapping_source IS 'Whether the suggested mapping came from a fresh AI analysis or a remembered mapping';
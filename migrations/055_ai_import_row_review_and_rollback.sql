@@ -0,0 +1,30 @@
+-- AI Import Row Review, Partial Commits and Rollback
+-- Rows can be accepted or rejected before import, a failing row no longer aborts its
+-- batch, and a completed import can be rolled back. Rollbacks reverse the imported
+-- stock and record each reversal in inventory_audit.
+
+CREATE TABLE IF NOT EXISTS ai_import_row_decisions (
+    session_id UUID NOT NULL REFERENCES ai_import_sessions(id) ON DELETE CASCADE,
+    row_number INTEGER NOT NULL CHECK (row_number > 0),
+    decision VARCHAR(10) NOT NULL CHECK (decision IN ('accept', 'reject')),
+    reason TEXT,
+    decided_by UUID NOT NULL REFERENCES users(id),
+    decided_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (session_id, row_number)
+);
+
+COMMENT ON TABLE ai_import_row_decisions IS 'Per-row accept/reject decisions made during mapping review; rows without a decision are imported';
+
+ALTER TABLE ai_import_sessions
+ADD COLUMN IF NOT EXISTS rows_rejected INTEGER DEFAULT 0,
+ADD COLUMN IF NOT EXISTS rows_rolled_back INTEGER DEFAULT 0,
+ADD COLUMN IF NOT EXISTS rolled_back_at TIMESTAMPTZ;
+
+-- Row statuses gain 'rejected' (skipped by the reviewer) and 'rolled_back'
+ALTER TABLE ai_import_row_results
+ADD COLUMN IF NOT EXISTS rolled_back_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_import_row_rollback
+    ON ai_import_row_results(session_id)
+    WHERE created_inventory_id IS NOT NULL AND rolled_back_at IS NULL;
@@ -0,0 +1,40 @@
+-- Regulatory Document Citations
+-- Every knowledge-base chunk retrieved for a generated document is stored with its
+-- provenance (regulation source/section, similarity, excerpt shown to the model, content
+-- hash at retrieval time) and whether the generated document cited it, so QA can check
+-- each requirement against its source.
+
+CREATE TABLE IF NOT EXISTS regulatory_document_citations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    document_id UUID NOT NULL REFERENCES regulatory_documents(id) ON DELETE CASCADE,
+
+    -- Reference label given to the model, e.g. 'R3'
+    citation_ref VARCHAR(10) NOT NULL,
+    knowledge_entry_id UUID REFERENCES regulatory_knowledge_base(id) ON DELETE SET NULL,
+
+    -- Provenance snapshot at generation time
+    regulation_source VARCHAR(200),
+    regulation_section VARCHAR(100),
+    section_title VARCHAR(500) NOT NULL,
+    similarity DOUBLE PRECISION NOT NULL,
+    excerpt TEXT NOT NULL,
+    content_hash VARCHAR(64) NOT NULL,  -- SHA-256 of the chunk content when retrieved
+
+    -- Whether the generated document cites this chunk, and for what
+    cited BOOLEAN NOT NULL DEFAULT FALSE,
+    applies_to JSONB NOT NULL DEFAULT '[]',
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    UNIQUE (document_id, citation_ref)
+);
+
+CREATE INDEX idx_reg_doc_citations_document ON regulatory_document_citations(document_id);
+CREATE INDEX idx_reg_doc_citations_entry ON regulatory_document_citations(knowledge_entry_id)
+WHERE knowledge_entry_id IS NOT NULL;
+
+-- References in the generated content that match no retrieved chunk
+ALTER TABLE regulatory_documents
+ADD COLUMN IF NOT EXISTS unresolved_citations JSONB NOT NULL DEFAULT '[]';
+
+COMMENT ON TABLE regulatory_document_citations IS 'Knowledge-base chunks retrieved (RAG) for a generated regulatory document and whether they were cited';
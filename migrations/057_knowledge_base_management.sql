@@ -0,0 +1,62 @@
+-- Regulatory Knowledge-Base Management
+-- Knowledge-base entries can be created, edited (re-embedded), deleted and bulk-imported
+-- through the API. Every change bumps the version of the entry's regulation source and
+-- appends the resulting entry state to an append-only history.
+
+CREATE TABLE IF NOT EXISTS regulatory_knowledge_sources (
+    regulation_source VARCHAR(200) PRIMARY KEY,
+    current_version INTEGER NOT NULL DEFAULT 1,
+    updated_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS regulatory_knowledge_source_versions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    regulation_source VARCHAR(200) NOT NULL REFERENCES regulatory_knowledge_sources(regulation_source) ON DELETE CASCADE,
+    version INTEGER NOT NULL,
+    change_type VARCHAR(20) NOT NULL CHECK (change_type IN ('seeded', 'created', 'updated', 'deleted', 'imported')),
+    entries_changed INTEGER NOT NULL DEFAULT 0,
+    notes TEXT,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    UNIQUE (regulation_source, version)
+);
+
+-- Entry state after each change (for deletes, the state that was removed)
+CREATE TABLE IF NOT EXISTS regulatory_knowledge_entry_history (
+    id BIGSERIAL PRIMARY KEY,
+    entry_id UUID NOT NULL,
+    regulation_source VARCHAR(200) NOT NULL,
+    source_version INTEGER NOT NULL,
+    change_type VARCHAR(20) NOT NULL CHECK (change_type IN ('created', 'updated', 'deleted', 'imported')),
+    document_type VARCHAR(50) NOT NULL,
+    regulation_section VARCHAR(100),
+    section_title VARCHAR(500) NOT NULL,
+    content TEXT NOT NULL,
+    metadata JSONB NOT NULL DEFAULT '{}',
+    changed_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_knowledge_history_entry ON regulatory_knowledge_entry_history(entry_id, id DESC);
+CREATE INDEX idx_knowledge_history_source ON regulatory_knowledge_entry_history(regulation_source, source_version);
+
+ALTER TABLE regulatory_knowledge_base
+ADD COLUMN IF NOT EXISTS source_version INTEGER NOT NULL DEFAULT 1,
+ADD COLUMN IF NOT EXISTS updated_by UUID REFERENCES users(id) ON DELETE SET NULL;
+
+-- Seeded sources start at version 1
+INSERT INTO regulatory_knowledge_sources (regulation_source)
+SELECT DISTINCT regulation_source FROM regulatory_knowledge_base WHERE regulation_source IS NOT NULL
+ON CONFLICT DO NOTHING;
+
+INSERT INTO regulatory_knowledge_source_versions (regulation_source, version, change_type, entries_changed, notes)
+SELECT regulation_source, 1, 'seeded', COUNT(*), 'Entries present before knowledge-base management'
+FROM regulatory_knowledge_base
+WHERE regulation_source IS NOT NULL
+GROUP BY regulation_source
+ON CONFLICT DO NOTHING;
+
+COMMENT ON TABLE regulatory_knowledge_sources IS 'Current version of each regulation source in the RAG knowledge base';
+COMMENT ON TABLE regulatory_knowledge_entry_history IS 'Append-only history of knowledge-base entry changes';
@@ -0,0 +1,57 @@
+-- Inventory & Transaction Anomaly Detection
+-- A scheduled detector flags sudden quantity jumps, listing and transaction prices far off
+-- market history, and duplicate-looking listings across accounts. Findings are reviewed
+-- by admins, who confirm or dismiss them.
+
+CREATE TABLE IF NOT EXISTS anomalies (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    anomaly_type VARCHAR(30) NOT NULL CHECK (anomaly_type IN (
+        'quantity_jump', 'listing_price_outlier', 'transaction_price_outlier', 'duplicate_listing'
+    )),
+    severity VARCHAR(10) NOT NULL CHECK (severity IN ('low', 'medium', 'high', 'critical')),
+    status VARCHAR(20) NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'confirmed', 'dismissed')),
+
+    -- Flagged record and the account it belongs to
+    entity_type VARCHAR(20) NOT NULL CHECK (entity_type IN ('inventory', 'transaction')),
+    entity_id UUID NOT NULL,
+    user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    pharmaceutical_id UUID REFERENCES pharmaceuticals(id) ON DELETE SET NULL,
+
+    -- Robust z-score, price ratio or similar; higher is more anomalous
+    score DOUBLE PRECISION NOT NULL,
+    description TEXT NOT NULL,
+    details JSONB NOT NULL DEFAULT '{}',
+
+    -- Same finding is never stored twice, whatever its review outcome
+    fingerprint VARCHAR(64) NOT NULL UNIQUE,
+
+    reviewed_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    reviewed_at TIMESTAMPTZ,
+    review_notes TEXT,
+
+    detected_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_anomalies_status ON anomalies(status, severity, detected_at DESC);
+CREATE INDEX idx_anomalies_entity ON anomalies(entity_type, entity_id);
+CREATE INDEX idx_anomalies_user ON anomalies(user_id) WHERE user_id IS NOT NULL;
+
+-- Quantity jumps need the quantity before each change. Only imports and rollbacks write
+-- inventory_audit today, so record every quantity change made to a listing.
+CREATE OR REPLACE FUNCTION log_inventory_quantity_change()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.quantity IS DISTINCT FROM OLD.quantity THEN
+        INSERT INTO inventory_audit (inventory_id, user_id, action, old_quantity, new_quantity, old_status, new_status)
+        VALUES (NEW.id, NEW.user_id, 'quantity_changed', OLD.quantity, NEW.quantity, OLD.status, NEW.status);
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS inventory_quantity_audit ON inventory;
+CREATE TRIGGER inventory_quantity_audit
+    AFTER UPDATE OF quantity ON inventory
+    FOR EACH ROW EXECUTE FUNCTION log_inventory_quantity_change();
+
+COMMENT ON TABLE anomalies IS 'Inventory and transaction anomalies flagged by the scheduled detector, with admin review';
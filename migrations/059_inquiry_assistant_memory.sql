@@ -0,0 +1,34 @@
+-- Inquiry Assistant Conversation Memory
+-- Each seller has an assistant thread per inquiry: every suggestion request and the
+-- suggestion it produced are stored as a turn, together with what the seller did with it
+-- (sent as is, sent edited, rejected with a reason). Follow-up suggestions replay recent
+-- turns within a token budget and condense older ones, so the assistant does not repeat
+-- offers that were already made or turned down.
+
+CREATE TABLE IF NOT EXISTS inquiry_assistant_turns (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    inquiry_id UUID NOT NULL REFERENCES inquiries(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    suggestion_id UUID NOT NULL UNIQUE REFERENCES inquiry_ai_suggestions(id) ON DELETE CASCADE,
+
+    suggestion_type VARCHAR(50) NOT NULL,
+    -- The seller's side of the turn: suggestion type and custom instructions
+    request_text TEXT NOT NULL,
+    response_text TEXT NOT NULL,
+
+    outcome VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (outcome IN ('pending', 'accepted', 'edited', 'rejected')),
+    -- Message actually sent when the suggestion was edited
+    sent_text TEXT,
+    rejection_reason TEXT,
+
+    -- Rough token size of request + response, used for the memory budget
+    estimated_tokens INTEGER NOT NULL DEFAULT 0,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_inquiry_assistant_turns_thread ON inquiry_assistant_turns(inquiry_id, user_id, created_at);
+
+COMMENT ON TABLE inquiry_assistant_turns IS 'Per-inquiry inquiry assistant conversation memory (suggestion requests, suggestions and their outcome)';
@@ -0,0 +1,54 @@
+-- Outbound Webhooks
+-- Users register HTTPS endpoints with an event filter. Platform events (inquiry.created,
+-- transaction.completed, inventory.low_stock, sync.finished) are queued as one delivery per
+-- matching endpoint, POSTed with an HMAC-SHA256 signature, and retried with exponential
+-- backoff. Every attempt is logged; finished deliveries can be replayed.
+
+CREATE TABLE IF NOT EXISTS webhook_endpoints (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    url TEXT NOT NULL,
+    description VARCHAR(255),
+    secret_encrypted TEXT NOT NULL,
+    event_types TEXT[] NOT NULL,
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    -- Failed deliveries in a row; the endpoint is disabled when it reaches the limit
+    consecutive_failures INTEGER NOT NULL DEFAULT 0,
+    disabled_reason TEXT,
+    last_delivery_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_webhook_endpoints_user ON webhook_endpoints(user_id);
+CREATE INDEX idx_webhook_endpoints_events ON webhook_endpoints USING GIN (event_types) WHERE is_active;
+
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    endpoint_id UUID NOT NULL REFERENCES webhook_endpoints(id) ON DELETE CASCADE,
+    -- Shared by every delivery of the same event (and by replays)
+    event_id UUID NOT NULL,
+    event_type VARCHAR(50) NOT NULL,
+    payload JSONB NOT NULL,
+
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'succeeded', 'failed')),
+    attempt_count INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMPTZ DEFAULT NOW(),
+    last_attempt_at TIMESTAMPTZ,
+    response_status INTEGER,
+    response_body TEXT,  -- first 2 KB
+    error_message TEXT,
+    duration_ms INTEGER,
+
+    replay_of UUID REFERENCES webhook_deliveries(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    delivered_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_webhook_deliveries_due ON webhook_deliveries(next_attempt_at) WHERE status = 'pending';
+CREATE INDEX idx_webhook_deliveries_endpoint ON webhook_deliveries(endpoint_id, created_at DESC);
+CREATE INDEX idx_webhook_deliveries_event ON webhook_deliveries(event_id);
+
+COMMENT ON TABLE webhook_endpoints IS 'User-registered outbound webhook endpoints with event filters';
+COMMENT ON TABLE webhook_deliveries IS 'Outbound webhook delivery queue and log (retries with backoff, replays)';
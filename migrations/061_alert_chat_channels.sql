@@ -0,0 +1,43 @@
+-- Alert Chat Channels (Slack / Microsoft Teams)
+-- Accounts (one per company) connect chat channels, e.g. a trading-desk channel, either
+-- with an incoming webhook URL or, for Slack, by installing their own OAuth app with the
+-- incoming-webhook scope. Alert preferences route each alert type to any number of
+-- channels; matching alerts are posted there as a message formatted for the alert type.
+
+CREATE TABLE IF NOT EXISTS alert_channels (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    provider VARCHAR(20) NOT NULL CHECK (provider IN ('slack', 'teams')),
+    connection_type VARCHAR(20) NOT NULL CHECK (connection_type IN ('incoming_webhook', 'oauth')),
+    name VARCHAR(255) NOT NULL,
+    status VARCHAR(30) NOT NULL DEFAULT 'active'
+        CHECK (status IN ('pending_authorization', 'active', 'error', 'disabled')),
+
+    -- Where messages are posted (encrypted); set on creation or by the OAuth install
+    webhook_url TEXT,
+    channel_name VARCHAR(255),
+    workspace_name VARCHAR(255),
+
+    -- OAuth app (encrypted) and authorization in progress
+    client_id TEXT,
+    client_secret TEXT,
+    oauth_state_hash VARCHAR(64),
+    oauth_state_expires_at TIMESTAMPTZ,
+    access_token TEXT,
+
+    last_error TEXT,
+    last_delivery_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_alert_channels_user ON alert_channels(user_id);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_alert_channels_state
+    ON alert_channels(oauth_state_hash) WHERE oauth_state_hash IS NOT NULL;
+
+-- Alert type -> channel ids, e.g. {"low_stock": ["<channel id>"], "new_inquiry": [...]}
+ALTER TABLE user_alert_preferences
+    ADD COLUMN IF NOT EXISTS channel_routes JSONB NOT NULL DEFAULT '{}'::jsonb;
+
+COMMENT ON TABLE alert_channels IS 'Slack / Microsoft Teams channels alerts can be routed to';
+COMMENT ON COLUMN user_alert_preferences.channel_routes IS 'Alert type -> alert_channels ids the alert is posted to';
@@ -0,0 +1,51 @@
+-- Real-time Notification Delivery
+-- New alert notifications and inquiry messages are announced on the 'atlas_realtime'
+-- NOTIFY channel with the row id and the users allowed to see it. Every API instance
+-- LISTENs and pushes the row to its open GET /api/alerts/stream connections, so delivery
+-- works no matter which instance wrote the row. NOTIFY is sent on commit; payloads stay
+-- far below the 8000 byte limit because only ids are sent.
+
+CREATE OR REPLACE FUNCTION notify_realtime_alert_notification()
+RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM pg_notify('atlas_realtime', json_build_object(
+        'kind', 'notification',
+        'id', NEW.id,
+        'user_ids', json_build_array(NEW.user_id)
+    )::text);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trigger_realtime_alert_notification ON alert_notifications;
+CREATE TRIGGER trigger_realtime_alert_notification
+    AFTER INSERT ON alert_notifications
+    FOR EACH ROW
+    EXECUTE FUNCTION notify_realtime_alert_notification();
+
+-- Both parties of the inquiry receive the message (the sender's other tabs included)
+CREATE OR REPLACE FUNCTION notify_realtime_inquiry_message()
+RETURNS TRIGGER AS $$
+DECLARE
+    v_buyer_id UUID;
+    v_seller_id UUID;
+BEGIN
+    SELECT i.buyer_id, inv.user_id INTO v_buyer_id, v_seller_id
+    FROM inquiries i
+    JOIN inventory inv ON inv.id = i.inventory_id
+    WHERE i.id = NEW.inquiry_id;
+
+    PERFORM pg_notify('atlas_realtime', json_build_object(
+        'kind', 'inquiry_message',
+        'id', NEW.id,
+        'user_ids', json_build_array(v_buyer_id, v_seller_id)
+    )::text);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trigger_realtime_inquiry_message ON inquiry_messages;
+CREATE TRIGGER trigger_realtime_inquiry_message
+    AFTER INSERT ON inquiry_messages
+    FOR EACH ROW
+    EXECUTE FUNCTION notify_realtime_inquiry_message();
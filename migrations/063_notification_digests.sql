@@ -0,0 +1,98 @@
+-- Notification Digests and Quiet Hours
+-- Users can batch non-critical notifications into a daily or weekly digest and/or set
+-- quiet hours in their own timezone. Such notifications are stored with held_until set:
+-- they stay out of the notification list, unread count, real-time stream and chat
+-- channels until the alert scheduler releases them, as one digest notification when
+-- several are due together. Critical notifications (recalls, critical expiry, ...) are
+-- never held.
+
+ALTER TABLE user_alert_preferences
+    ADD COLUMN IF NOT EXISTS digest_frequency VARCHAR(10) NOT NULL DEFAULT 'off'
+        CHECK (digest_frequency IN ('off', 'daily', 'weekly')),
+    ADD COLUMN IF NOT EXISTS digest_hour INTEGER NOT NULL DEFAULT 8
+        CHECK (digest_hour >= 0 AND digest_hour <= 23),
+    -- ISO weekday for weekly digests (1 = Monday ... 7 = Sunday)
+    ADD COLUMN IF NOT EXISTS digest_weekday INTEGER NOT NULL DEFAULT 1
+        CHECK (digest_weekday >= 1 AND digest_weekday <= 7),
+    ADD COLUMN IF NOT EXISTS quiet_hours_enabled BOOLEAN NOT NULL DEFAULT FALSE,
+    -- Local hours; the window may wrap midnight (22 -> 7)
+    ADD COLUMN IF NOT EXISTS quiet_hours_start INTEGER NOT NULL DEFAULT 22
+        CHECK (quiet_hours_start >= 0 AND quiet_hours_start <= 23),
+    ADD COLUMN IF NOT EXISTS quiet_hours_end INTEGER NOT NULL DEFAULT 7
+        CHECK (quiet_hours_end >= 0 AND quiet_hours_end <= 23),
+    -- IANA timezone name (validated against pg_timezone_names)
+    ADD COLUMN IF NOT EXISTS timezone VARCHAR(64) NOT NULL DEFAULT 'UTC';
+
+ALTER TABLE alert_notifications
+    ADD COLUMN IF NOT EXISTS held_until TIMESTAMPTZ,
+    -- Digest notification a released notification was summarized in
+    ADD COLUMN IF NOT EXISTS digest_id UUID REFERENCES alert_notifications(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_alert_notifications_held
+    ON alert_notifications(held_until) WHERE held_until IS NOT NULL;
+
+ALTER TABLE alert_notifications DROP CONSTRAINT IF EXISTS alert_notifications_alert_type_check;
+ALTER TABLE alert_notifications ADD CONSTRAINT alert_notifications_alert_type_check
+CHECK (alert_type IN (
+    'expiry_warning',
+    'expiry_critical',
+    'low_stock',
+    'watchlist_match',
+    'price_drop',
+    'new_inquiry',
+    'inquiry_message',
+    'catalog_change',
+    'report_ready',
+    'digest',
+    'system'
+));
+
+ALTER TABLE alert_processing_log DROP CONSTRAINT IF EXISTS alert_processing_log_run_type_check;
+ALTER TABLE alert_processing_log ADD CONSTRAINT alert_processing_log_run_type_check
+CHECK (run_type IN (
+    'expiry_check',
+    'low_stock_check',
+    'watchlist_check',
+    'scheduled_run',
+    'digest_delivery'
+));
+
+-- Held notifications are not unread yet
+CREATE OR REPLACE FUNCTION get_unread_alert_count(p_user_id UUID)
+RETURNS INTEGER AS $$
+    SELECT COUNT(*)::INTEGER
+    FROM alert_notifications
+    WHERE user_id = p_user_id
+      AND is_read = FALSE
+      AND is_dismissed = FALSE
+      AND held_until IS NULL;
+$$ LANGUAGE SQL STABLE;
+
+-- Real-time push: announce notifications when they become visible (on insert, or on
+-- release of a held notification), but not ones released as read into a digest
+CREATE OR REPLACE FUNCTION notify_realtime_alert_notification()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.held_until IS NOT NULL OR NEW.is_read THEN
+        RETURN NEW;
+    END IF;
+    IF TG_OP = 'UPDATE' AND OLD.held_until IS NULL THEN
+        RETURN NEW;
+    END IF;
+
+    PERFORM pg_notify('atlas_realtime', json_build_object(
+        'kind', 'notification',
+        'id', NEW.id,
+        'user_ids', json_build_array(NEW.user_id)
+    )::text);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trigger_realtime_alert_notification ON alert_notifications;
+CREATE TRIGGER trigger_realtime_alert_notification
+    AFTER INSERT OR UPDATE OF held_until ON alert_notifications
+    FOR EACH ROW
+    EXECUTE FUNCTION notify_realtime_alert_notification();
+
+COMMENT ON COLUMN alert_notifications.held_until IS 'Digest / quiet hours: hidden until released by the alert scheduler';
@@ -0,0 +1,63 @@
+-- Watchlist Triggers (price drop / back in stock)
+-- Besides the saved-search match count, a watchlist can carry triggers on a specific NDC:
+--   price_drop     - the best available marketplace price is at or below price_threshold
+--                    (alerts on crossing the threshold and on each further drop)
+--   back_in_stock  - the NDC is listed again after the last check found no listing
+-- The alert scheduler records what it observed on each check. A trigger that fires
+-- again within its cooldown is held back without advancing that state, so the alert
+-- goes out once the cooldown has passed if the condition still holds.
+
+CREATE TABLE IF NOT EXISTS watchlist_triggers (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    watchlist_id UUID NOT NULL REFERENCES marketplace_watchlist(id) ON DELETE CASCADE,
+    trigger_type VARCHAR(20) NOT NULL CHECK (trigger_type IN ('price_drop', 'back_in_stock')),
+    ndc_code VARCHAR(20) NOT NULL,
+    price_threshold DECIMAL(12,2) CHECK (price_threshold >= 0),
+    cooldown_hours INTEGER NOT NULL DEFAULT 24 CHECK (cooldown_hours >= 1 AND cooldown_hours <= 720),
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+
+    -- Observed on the last evaluation
+    last_best_price DECIMAL(12,2),
+    last_in_stock BOOLEAN,
+    last_evaluated_at TIMESTAMPTZ,
+
+    last_triggered_at TIMESTAMPTZ,
+    trigger_count INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CHECK (trigger_type <> 'price_drop' OR price_threshold IS NOT NULL)
+);
+
+CREATE INDEX IF NOT EXISTS idx_watchlist_triggers_watchlist ON watchlist_triggers(watchlist_id);
+CREATE INDEX IF NOT EXISTS idx_watchlist_triggers_active ON watchlist_triggers(ndc_code) WHERE is_active;
+
+ALTER TABLE alert_notifications DROP CONSTRAINT IF EXISTS alert_notifications_alert_type_check;
+ALTER TABLE alert_notifications ADD CONSTRAINT alert_notifications_alert_type_check
+CHECK (alert_type IN (
+    'expiry_warning',
+    'expiry_critical',
+    'low_stock',
+    'watchlist_match',
+    'price_drop',
+    'back_in_stock',
+    'new_inquiry',
+    'inquiry_message',
+    'catalog_change',
+    'report_ready',
+    'digest',
+    'system'
+));
+
+ALTER TABLE alert_processing_log DROP CONSTRAINT IF EXISTS alert_processing_log_run_type_check;
+ALTER TABLE alert_processing_log ADD CONSTRAINT alert_processing_log_run_type_check
+CHECK (run_type IN (
+    'expiry_check',
+    'low_stock_check',
+    'watchlist_check',
+    'watchlist_trigger_check',
+    'scheduled_run',
+    'digest_delivery'
+));
+
+COMMENT ON TABLE watchlist_triggers IS 'Per-NDC price drop / back in stock triggers on marketplace watchlists';
@@ -0,0 +1,43 @@
+-- Alert Scheduler Jobs
+-- Every API instance runs the alert scheduler. To keep replicas from running the same
+-- check twice, each check type is a row here: an instance claims a due job by taking
+-- its lease in a single UPDATE, runs it, and schedules the next run on release. A lease
+-- left behind by a crashed instance expires after lease_seconds.
+-- next_run_at is persisted, so after downtime an overdue job runs once on startup
+-- (missed runs are coalesced) and then continues on its regular cadence.
+-- Intervals are per check type and can be tuned here without a redeploy.
+
+CREATE TABLE IF NOT EXISTS alert_scheduler_jobs (
+    job_type VARCHAR(50) PRIMARY KEY CHECK (job_type IN (
+        'expiry_check',
+        'low_stock_check',
+        'watchlist_check',
+        'watchlist_trigger_check',
+        'digest_delivery'
+    )),
+    interval_seconds INTEGER NOT NULL CHECK (interval_seconds >= 60),
+    lease_seconds INTEGER NOT NULL DEFAULT 1800 CHECK (lease_seconds >= 60),
+    is_enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    next_run_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    -- Current lease (instance id of the runner)
+    lease_owner VARCHAR(255),
+    lease_expires_at TIMESTAMPTZ,
+
+    last_started_at TIMESTAMPTZ,
+    last_completed_at TIMESTAMPTZ,
+    last_status VARCHAR(20) CHECK (last_status IN ('completed', 'failed')),
+    last_error TEXT,
+    run_count INTEGER NOT NULL DEFAULT 0,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+INSERT INTO alert_scheduler_jobs (job_type, interval_seconds, lease_seconds) VALUES
+    ('expiry_check', 3600, 1800),
+    ('low_stock_check', 3600, 1800),
+    ('watchlist_check', 3600, 1800),
+    ('watchlist_trigger_check', 900, 600),
+    ('digest_delivery', 300, 240)
+ON CONFLICT (job_type) DO NOTHING;
+
+COMMENT ON TABLE alert_scheduler_jobs IS 'Per check type schedule and lease of the alert scheduler (one runner across replicas)';
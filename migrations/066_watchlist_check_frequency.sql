@@ -0,0 +1,21 @@
+-- Per-Watchlist Check Frequency and Pause
+-- Each watchlist is evaluated on its own cadence (every 15 minutes, hourly or daily):
+-- the watchlist check job picks up watchlists whose next_check_at has passed, users on
+-- paid plan tiers first. A paused watchlist (and its triggers) is not evaluated until
+-- resumed; resuming makes it due immediately.
+
+ALTER TABLE marketplace_watchlist
+    ADD COLUMN IF NOT EXISTS check_frequency VARCHAR(10) NOT NULL DEFAULT 'hourly'
+        CHECK (check_frequency IN ('15min', 'hourly', 'daily')),
+    ADD COLUMN IF NOT EXISTS paused_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS next_check_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+
+CREATE INDEX IF NOT EXISTS idx_watchlist_next_check
+    ON marketplace_watchlist(next_check_at) WHERE paused_at IS NULL;
+
+-- The job has to run at least as often as the most frequent watchlist cadence
+UPDATE alert_scheduler_jobs SET interval_seconds = 900, updated_at = NOW()
+WHERE job_type = 'watchlist_check' AND interval_seconds > 900;
+
+COMMENT ON COLUMN marketplace_watchlist.check_frequency IS 'Evaluation cadence: 15min, hourly or daily';
+COMMENT ON COLUMN marketplace_watchlist.paused_at IS 'Set while the watchlist is paused (not evaluated)';
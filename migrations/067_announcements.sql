@@ -0,0 +1,120 @@
+-- Platform Announcements
+-- Admins publish announcements (maintenance windows, new regulations, ...) to all users
+-- or to users with given roles and/or in given countries. Publishing creates an
+-- 'announcement' notification per recipient, so announcements go through the regular
+-- notification pipeline (quiet hours, real-time stream, chat channels). Announcements
+-- scheduled for later are published by the alert scheduler. Users read them in the
+-- /api/alerts/announcements feed; reads are tracked per user.
+
+-- Country of the account (ISO 3166-1 alpha-2), used for announcement targeting
+ALTER TABLE users ADD COLUMN IF NOT EXISTS country_code VARCHAR(2);
+CREATE INDEX IF NOT EXISTS idx_users_country_code ON users(country_code);
+
+CREATE TABLE IF NOT EXISTS announcements (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    title VARCHAR(200) NOT NULL,
+    message TEXT NOT NULL,
+    category VARCHAR(20) NOT NULL DEFAULT 'general'
+        CHECK (category IN ('general', 'maintenance', 'regulatory', 'product')),
+    severity VARCHAR(20) NOT NULL DEFAULT 'info'
+        CHECK (severity IN ('info', 'warning', 'critical')),
+    link_url TEXT,
+
+    -- Empty = everyone
+    target_roles TEXT[] NOT NULL DEFAULT '{}',
+    target_countries TEXT[] NOT NULL DEFAULT '{}',
+
+    publish_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ,
+    published_at TIMESTAMPTZ,
+    withdrawn_at TIMESTAMPTZ,
+    recipient_count INTEGER NOT NULL DEFAULT 0,
+
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CHECK (expires_at IS NULL OR expires_at > publish_at)
+);
+
+CREATE INDEX IF NOT EXISTS idx_announcements_published ON announcements(published_at DESC)
+    WHERE published_at IS NOT NULL AND withdrawn_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_announcements_due ON announcements(publish_at)
+    WHERE published_at IS NULL AND withdrawn_at IS NULL;
+
+CREATE TABLE IF NOT EXISTS announcement_reads (
+    announcement_id UUID NOT NULL REFERENCES announcements(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    read_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (announcement_id, user_id)
+);
+
+ALTER TABLE alert_notifications DROP CONSTRAINT IF EXISTS alert_notifications_alert_type_check;
+ALTER TABLE alert_notifications ADD CONSTRAINT alert_notifications_alert_type_check
+CHECK (alert_type IN (
+    'expiry_warning',
+    'expiry_critical',
+    'low_stock',
+    'watchlist_match',
+    'price_drop',
+    'back_in_stock',
+    'new_inquiry',
+    'inquiry_message',
+    'catalog_change',
+    'report_ready',
+    'digest',
+    'announcement',
+    'system'
+));
+
+ALTER TABLE alert_processing_log DROP CONSTRAINT IF EXISTS alert_processing_log_run_type_check;
+ALTER TABLE alert_processing_log ADD CONSTRAINT alert_processing_log_run_type_check
+CHECK (run_type IN (
+    'expiry_check',
+    'low_stock_check',
+    'watchlist_check',
+    'watchlist_trigger_check',
+    'scheduled_run',
+    'digest_delivery',
+    'announcement_delivery'
+));
+
+ALTER TABLE alert_scheduler_jobs DROP CONSTRAINT IF EXISTS alert_scheduler_jobs_job_type_check;
+ALTER TABLE alert_scheduler_jobs ADD CONSTRAINT alert_scheduler_jobs_job_type_check
+CHECK (job_type IN (
+    'expiry_check',
+    'low_stock_check',
+    'watchlist_check',
+    'watchlist_trigger_check',
+    'digest_delivery',
+    'announcement_delivery'
+));
+
+INSERT INTO alert_scheduler_jobs (job_type, interval_seconds, lease_seconds)
+VALUES ('announcement_delivery', 60, 600)
+ON CONFLICT (job_type) DO NOTHING;
+
+-- Reading the announcement notification counts as reading the announcement
+CREATE OR REPLACE FUNCTION record_announcement_read()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.alert_type = 'announcement' AND NEW.is_read AND NOT OLD.is_read
+       AND NEW.metadata ? 'announcement_id' THEN
+        INSERT INTO announcement_reads (announcement_id, user_id)
+        SELECT a.id, NEW.user_id
+        FROM announcements a
+        WHERE a.id = (NEW.metadata->>'announcement_id')::uuid
+        ON CONFLICT DO NOTHING;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trigger_record_announcement_read ON alert_notifications;
+CREATE TRIGGER trigger_record_announcement_read
+    AFTER UPDATE OF is_read ON alert_notifications
+    FOR EACH ROW
+    EXECUTE FUNCTION record_announcement_read();
+
+COMMENT ON TABLE announcements IS 'Admin platform announcements, targeted by role and country';
+COMMENT ON COLUMN users.country_code IS 'ISO 3166-1 alpha-2 country of the account (announcement targeting)';
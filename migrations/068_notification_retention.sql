@@ -0,0 +1,54 @@
+-- Notification Retention and Archival
+-- The alert scheduler archives notifications older than NOTIFICATION_ARCHIVE_AFTER_DAYS
+-- (default 90) and deletes them after NOTIFICATION_DELETE_AFTER_DAYS (default 365).
+-- Archived notifications leave the notification list and the unread count and are
+-- listed under GET /api/alerts/notifications/archived until deleted.
+
+ALTER TABLE alert_notifications ADD COLUMN IF NOT EXISTS archived_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_alert_notifications_archived
+    ON alert_notifications(user_id, created_at DESC) WHERE archived_at IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_alert_notifications_retention
+    ON alert_notifications(created_at) WHERE archived_at IS NULL AND held_until IS NULL;
+
+CREATE OR REPLACE FUNCTION get_unread_alert_count(p_user_id UUID)
+RETURNS INTEGER AS $$
+    SELECT COUNT(*)::INTEGER
+    FROM alert_notifications
+    WHERE user_id = p_user_id
+      AND is_read = FALSE
+      AND is_dismissed = FALSE
+      AND held_until IS NULL
+      AND archived_at IS NULL;
+$$ LANGUAGE SQL STABLE;
+
+ALTER TABLE alert_processing_log DROP CONSTRAINT IF EXISTS alert_processing_log_run_type_check;
+ALTER TABLE alert_processing_log ADD CONSTRAINT alert_processing_log_run_type_check
+CHECK (run_type IN (
+    'expiry_check',
+    'low_stock_check',
+    'watchlist_check',
+    'watchlist_trigger_check',
+    'scheduled_run',
+    'digest_delivery',
+    'announcement_delivery',
+    'notification_retention'
+));
+
+ALTER TABLE alert_scheduler_jobs DROP CONSTRAINT IF EXISTS alert_scheduler_jobs_job_type_check;
+ALTER TABLE alert_scheduler_jobs ADD CONSTRAINT alert_scheduler_jobs_job_type_check
+CHECK (job_type IN (
+    'expiry_check',
+    'low_stock_check',
+    'watchlist_check',
+    'watchlist_trigger_check',
+    'digest_delivery',
+    'announcement_delivery',
+    'notification_retention'
+));
+
+INSERT INTO alert_scheduler_jobs (job_type, interval_seconds, lease_seconds)
+VALUES ('notification_retention', 21600, 3600)
+ON CONFLICT (job_type) DO NOTHING;
+
+COMMENT ON COLUMN alert_notifications.archived_at IS 'Set by the retention job; archived notifications are hidden from the active list and unread count';
@@ -0,0 +1,24 @@
+-- DSCSA Transaction Data
+-- For sales between US trading partners the seller generates the DSCSA (FD&C Act §582)
+-- Transaction Information, Transaction History and Transaction Statement from the
+-- catalog and lot data of the transaction. They are regulatory documents (document
+-- types DSCSA_TI, DSCSA_TH, DSCSA_TS): signed on generation, recorded in the
+-- regulatory document ledger, and approved through the regular approval flow.
+-- Each one is attached to the transaction's document vault (transaction_documents).
+
+-- Vault entries come either from a reviewed extraction or from a regulatory document
+ALTER TABLE transaction_documents ALTER COLUMN extraction_id DROP NOT NULL;
+ALTER TABLE transaction_documents
+    ADD COLUMN IF NOT EXISTS regulatory_document_id UUID UNIQUE
+        REFERENCES regulatory_documents(id) ON DELETE RESTRICT;
+
+ALTER TABLE transaction_documents DROP CONSTRAINT IF EXISTS transaction_documents_source_check;
+ALTER TABLE transaction_documents ADD CONSTRAINT transaction_documents_source_check
+CHECK (num_nonnulls(extraction_id, regulatory_document_id) = 1);
+
+-- One TI, TH and TS per transaction
+CREATE UNIQUE INDEX IF NOT EXISTS idx_transaction_documents_dscsa
+    ON transaction_documents(transaction_id, document_kind)
+    WHERE document_kind IN ('dscsa_ti', 'dscsa_th', 'dscsa_ts');
+
+COMMENT ON COLUMN transaction_documents.regulatory_document_id IS 'Generated regulatory document (DSCSA TI/TH/TS) kept in the vault';
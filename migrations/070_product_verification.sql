@@ -0,0 +1,50 @@
+-- Product Verification (VRS)
+-- Serial numbers of inventory lots (GTIN + serial, as encoded in the DSCSA product
+-- identifier) and a verification lookup against them in the style of the Verification
+-- Router Service: a returns processor sends GTIN, serial, lot and expiry and gets back
+-- verified / not_found / recalled. Every lookup is logged with its outcome and response
+-- time for the audit trail and SLA reporting.
+
+CREATE TABLE IF NOT EXISTS inventory_serial_numbers (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    inventory_id UUID NOT NULL REFERENCES inventory(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    gtin VARCHAR(14) NOT NULL,
+    serial_number VARCHAR(20) NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'active'
+        CHECK (status IN ('active', 'shipped', 'decommissioned')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (gtin, serial_number)
+);
+
+CREATE INDEX IF NOT EXISTS idx_inventory_serial_numbers_inventory ON inventory_serial_numbers(inventory_id);
+
+CREATE TABLE IF NOT EXISTS product_verification_requests (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    requester_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- Requestor's correlation id, echoed back
+    correlation_id VARCHAR(100),
+
+    gtin VARCHAR(14) NOT NULL,
+    serial_number VARCHAR(20) NOT NULL,
+    lot_number VARCHAR(100) NOT NULL,
+    expiry_date DATE NOT NULL,
+
+    status VARCHAR(20) NOT NULL CHECK (status IN ('verified', 'not_found', 'recalled')),
+    additional_info VARCHAR(30),
+    serial_id UUID REFERENCES inventory_serial_numbers(id) ON DELETE SET NULL,
+    recall_number VARCHAR(50),
+
+    response_time_ms INTEGER NOT NULL,
+    within_sla BOOLEAN NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_product_verification_requester
+    ON product_verification_requests(requester_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_product_verification_created
+    ON product_verification_requests(created_at DESC);
+
+COMMENT ON TABLE inventory_serial_numbers IS 'Serialized units (GTIN + serial) of inventory lots';
+COMMENT ON TABLE product_verification_requests IS 'Audit log of VRS-style product verification lookups';
@@ -0,0 +1,58 @@
+-- 21 CFR Part 11 Electronic Signatures
+-- Regulatory documents are signed by an ordered sequence of signers, each with a stated
+-- meaning (authorship, review, approval, responsibility). A signer re-authenticates
+-- (password, plus TOTP when MFA is enabled) at signing; the signature manifest (printed
+-- name, meaning, timestamp, document content hash) is signed with the signer's Ed25519
+-- key, recorded in the regulatory document ledger, and cannot be changed afterwards.
+-- The document is approved when the last signer in the sequence has signed.
+
+CREATE TABLE IF NOT EXISTS regulatory_document_signatures (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    document_id UUID NOT NULL REFERENCES regulatory_documents(id) ON DELETE RESTRICT,
+    sequence_number INTEGER NOT NULL CHECK (sequence_number >= 1),
+    signer_id UUID NOT NULL REFERENCES users(id) ON DELETE RESTRICT,
+    meaning VARCHAR(20) NOT NULL
+        CHECK (meaning IN ('authorship', 'review', 'approval', 'responsibility')),
+    status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'signed')),
+
+    -- Set on signing
+    signer_name VARCHAR(255),
+    signed_at TIMESTAMPTZ,
+    content_hash VARCHAR(64),
+    manifest JSONB,
+    manifest_hash VARCHAR(64),
+    signature VARCHAR(128),
+    signature_public_key VARCHAR(64),
+    auth_method VARCHAR(20) CHECK (auth_method IN ('password', 'password_totp')),
+
+    requested_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    UNIQUE (document_id, sequence_number),
+    CHECK (status = 'pending' OR (signed_at IS NOT NULL AND manifest IS NOT NULL AND signature IS NOT NULL))
+);
+
+CREATE INDEX IF NOT EXISTS idx_document_signatures_signer
+    ON regulatory_document_signatures(signer_id) WHERE status = 'pending';
+
+-- Signed manifests are immutable; pending slots can be signed or removed
+CREATE OR REPLACE FUNCTION protect_signed_document_signature()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF OLD.status = 'signed' THEN
+        RAISE EXCEPTION 'Electronic signature % is signed and cannot be changed', OLD.id;
+    END IF;
+    IF TG_OP = 'DELETE' THEN
+        RETURN OLD;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trigger_protect_signed_document_signature ON regulatory_document_signatures;
+CREATE TRIGGER trigger_protect_signed_document_signature
+    BEFORE UPDATE OR DELETE ON regulatory_document_signatures
+    FOR EACH ROW
+    EXECUTE FUNCTION protect_signed_document_signature();
+
+COMMENT ON TABLE regulatory_document_signatures IS '21 CFR Part 11 signing sequence and signature manifests of regulatory documents';
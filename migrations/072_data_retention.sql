@@ -0,0 +1,145 @@
+-- Data Retention and Purge
+-- Superadmins set a retention period per data category; the daily data_purge job of
+-- the alert scheduler (or a manual run) deletes rows older than the period of every
+-- enabled category. Legal holds keep data from being purged, either for one user (the
+-- subject of the rows) or for a whole category. Each run records what it purged and
+-- what it kept per category.
+--
+-- Categories:
+--   audit_logs          audit_logs (subject: actor_user_id)
+--   notifications       alert_notifications (subject: user_id)
+--   sync_logs           openfda_sync_log, ema_sync_log, erp_sync_logs (subject: triggered_by_user_id)
+--   messages            inquiry_messages (subject: inquiry buyer and seller)
+--   archived_inventory  sold/expired inventory lots no longer referenced by inquiries
+--                       or AI imports (subject: user_id)
+
+CREATE TABLE IF NOT EXISTS data_retention_policies (
+    category VARCHAR(30) PRIMARY KEY CHECK (category IN (
+        'audit_logs',
+        'notifications',
+        'sync_logs',
+        'messages',
+        'archived_inventory'
+    )),
+    retention_days INTEGER NOT NULL CHECK (retention_days >= 30),
+    is_enabled BOOLEAN NOT NULL DEFAULT FALSE,
+    updated_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Purging is opt-in: every category starts disabled
+INSERT INTO data_retention_policies (category, retention_days) VALUES
+    ('audit_logs', 2555),
+    ('notifications', 365),
+    ('sync_logs', 180),
+    ('messages', 1095),
+    ('archived_inventory', 730)
+ON CONFLICT (category) DO NOTHING;
+
+CREATE TABLE IF NOT EXISTS data_legal_holds (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- NULL: every category
+    category VARCHAR(30) CHECK (category IN (
+        'audit_logs',
+        'notifications',
+        'sync_logs',
+        'messages',
+        'archived_inventory'
+    )),
+    -- NULL: all data of the category
+    user_id UUID REFERENCES users(id) ON DELETE RESTRICT,
+    reason TEXT NOT NULL,
+    reference VARCHAR(255),
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    released_at TIMESTAMPTZ,
+    released_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    release_reason TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_data_legal_holds_active
+    ON data_legal_holds(category) WHERE released_at IS NULL;
+
+CREATE TABLE IF NOT EXISTS data_purge_runs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    status VARCHAR(20) NOT NULL DEFAULT 'running'
+        CHECK (status IN ('running', 'completed', 'failed')),
+    -- NULL: scheduled run
+    triggered_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ,
+    total_purged BIGINT NOT NULL DEFAULT 0,
+    -- Per category: cutoff, purged and retained counts, or why it was skipped
+    report JSONB NOT NULL DEFAULT '[]'::jsonb,
+    error TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_data_purge_runs_started ON data_purge_runs(started_at DESC);
+
+-- One purge at a time
+CREATE UNIQUE INDEX IF NOT EXISTS idx_data_purge_runs_running
+    ON data_purge_runs((TRUE)) WHERE status = 'running';
+
+CREATE INDEX IF NOT EXISTS idx_audit_logs_created_at ON audit_logs(created_at);
+CREATE INDEX IF NOT EXISTS idx_inquiry_messages_created_at ON inquiry_messages(created_at);
+
+-- Audit logs stay immutable; deletes are allowed past retention_until, or by the purge
+-- job, which sets atlas.retention_purge_before (transaction-local) to its cutoff
+CREATE OR REPLACE FUNCTION prevent_audit_log_modification()
+RETURNS TRIGGER AS $$
+DECLARE
+    purge_before TIMESTAMPTZ;
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        purge_before := NULLIF(current_setting('atlas.retention_purge_before', TRUE), '')::TIMESTAMPTZ;
+        IF purge_before IS NOT NULL AND OLD.created_at < purge_before THEN
+            RETURN OLD;
+        END IF;
+        -- Only allow deletion of logs past retention period
+        IF OLD.retention_until IS NULL OR OLD.retention_until > NOW() THEN
+            RAISE EXCEPTION 'Audit logs cannot be deleted before retention period expires';
+        END IF;
+        RETURN OLD;
+    ELSIF TG_OP = 'UPDATE' THEN
+        -- Audit logs are immutable - no updates allowed
+        RAISE EXCEPTION 'Audit logs are immutable and cannot be updated';
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+ALTER TABLE alert_processing_log DROP CONSTRAINT IF EXISTS alert_processing_log_run_type_check;
+ALTER TABLE alert_processing_log ADD CONSTRAINT alert_processing_log_run_type_check
+CHECK (run_type IN (
+    'expiry_check',
+    'low_stock_check',
+    'watchlist_check',
+    'watchlist_trigger_check',
+    'scheduled_run',
+    'digest_delivery',
+    'announcement_delivery',
+    'notification_retention',
+    'data_purge'
+));
+
+ALTER TABLE alert_scheduler_jobs DROP CONSTRAINT IF EXISTS alert_scheduler_jobs_job_type_check;
+ALTER TABLE alert_scheduler_jobs ADD CONSTRAINT alert_scheduler_jobs_job_type_check
+CHECK (job_type IN (
+    'expiry_check',
+    'low_stock_check',
+    'watchlist_check',
+    'watchlist_trigger_check',
+    'digest_delivery',
+    'announcement_delivery',
+    'notification_retention',
+    'data_purge'
+));
+
+INSERT INTO alert_scheduler_jobs (job_type, interval_seconds, lease_seconds)
+VALUES ('data_purge', 86400, 7200)
+ON CONFLICT (job_type) DO NOTHING;
+
+COMMENT ON TABLE data_retention_policies IS 'Retention period per data category, applied by the data_purge job when enabled';
+COMMENT ON TABLE data_legal_holds IS 'Legal holds exempting a user''s data, or a whole category, from retention purges';
+COMMENT ON TABLE data_purge_runs IS 'Retention purge runs and what each purged per category';
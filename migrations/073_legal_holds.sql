@@ -0,0 +1,81 @@
+-- Legal Hold Management
+-- Admins place legal holds on a user (company), on a set of transactions, or on all
+-- data of a category. While a hold is active its data is exempt from retention purges
+-- and the held user (or a party to a held transaction) cannot be deleted. Holds are
+-- released, never deleted; placing, releasing and every blocked deletion are written
+-- to the audit log.
+
+ALTER TABLE data_legal_holds
+    ADD COLUMN IF NOT EXISTS target_type VARCHAR(20) NOT NULL DEFAULT 'all'
+        CHECK (target_type IN ('all', 'user', 'transactions')),
+    ADD COLUMN IF NOT EXISTS transaction_ids UUID[],
+    -- Company name at the time the hold was placed, kept if the user is deleted later
+    ADD COLUMN IF NOT EXISTS subject_name VARCHAR(255);
+
+UPDATE data_legal_holds SET target_type = 'user' WHERE user_id IS NOT NULL;
+
+-- Released holds must not stop the user from being deleted afterwards; active holds
+-- are protected by the trigger below
+ALTER TABLE data_legal_holds DROP CONSTRAINT IF EXISTS data_legal_holds_user_id_fkey;
+ALTER TABLE data_legal_holds ADD CONSTRAINT data_legal_holds_user_id_fkey
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE SET NULL;
+
+ALTER TABLE data_legal_holds ADD CONSTRAINT data_legal_holds_target_check CHECK (
+    (target_type = 'all' AND user_id IS NULL AND transaction_ids IS NULL)
+    OR (target_type = 'user' AND transaction_ids IS NULL AND (user_id IS NOT NULL OR released_at IS NOT NULL))
+    OR (target_type = 'transactions' AND user_id IS NULL AND cardinality(transaction_ids) >= 1)
+);
+
+CREATE INDEX IF NOT EXISTS idx_data_legal_holds_user
+    ON data_legal_holds(user_id) WHERE released_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_data_legal_holds_transactions
+    ON data_legal_holds USING GIN (transaction_ids) WHERE released_at IS NULL;
+
+-- Whether a user's data is under an active hold, directly or through a held transaction
+CREATE OR REPLACE FUNCTION user_under_legal_hold(p_user_id UUID)
+RETURNS BOOLEAN AS $$
+    SELECT EXISTS (
+        SELECT 1 FROM data_legal_holds h
+        WHERE h.released_at IS NULL
+          AND (
+              h.user_id = p_user_id
+              OR (h.target_type = 'transactions' AND EXISTS (
+                  SELECT 1 FROM transactions t
+                  WHERE t.id = ANY(h.transaction_ids)
+                    AND (t.seller_id = p_user_id OR t.buyer_id = p_user_id)
+              ))
+          )
+    );
+$$ LANGUAGE SQL STABLE;
+
+CREATE OR REPLACE FUNCTION prevent_held_user_deletion()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF user_under_legal_hold(OLD.id) THEN
+        RAISE EXCEPTION 'User % is under legal hold and cannot be deleted', OLD.id;
+    END IF;
+    RETURN OLD;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trigger_prevent_held_user_deletion ON users;
+CREATE TRIGGER trigger_prevent_held_user_deletion
+    BEFORE DELETE ON users
+    FOR EACH ROW
+    EXECUTE FUNCTION prevent_held_user_deletion();
+
+-- Holds are released, not deleted
+CREATE OR REPLACE FUNCTION prevent_legal_hold_deletion()
+RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'Legal hold % cannot be deleted; release it instead', OLD.id;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trigger_prevent_legal_hold_deletion ON data_legal_holds;
+CREATE TRIGGER trigger_prevent_legal_hold_deletion
+    BEFORE DELETE ON data_legal_holds
+    FOR EACH ROW
+    EXECUTE FUNCTION prevent_legal_hold_deletion();
+
+COMMENT ON TABLE data_legal_holds IS 'Legal holds on a user, a set of transactions or a whole data category; exempt from purges and block deletion until released';
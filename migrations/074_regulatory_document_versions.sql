@@ -0,0 +1,53 @@
+-- Regulatory Document Versioning and Change Control
+-- A document is revised into a new version (a draft) linked to the version it replaces,
+-- with the reason for the change. When the new version is approved the previous one is
+-- superseded; an approved document can also be made obsolete without a replacement.
+-- Versions of one document share a root (version 1) and a base document number; each
+-- revision, supersession and obsoletion is recorded in the regulatory document ledger.
+--
+-- Statuses: draft, pending_approval, approved, rejected, voided, superseded, obsolete
+
+ALTER TABLE regulatory_documents
+    ADD COLUMN IF NOT EXISTS root_document_id UUID REFERENCES regulatory_documents(id) ON DELETE RESTRICT,
+    ADD COLUMN IF NOT EXISTS version_number INTEGER NOT NULL DEFAULT 1 CHECK (version_number >= 1),
+    ADD COLUMN IF NOT EXISTS previous_version_id UUID UNIQUE REFERENCES regulatory_documents(id) ON DELETE RESTRICT,
+    ADD COLUMN IF NOT EXISTS change_reason TEXT,
+    ADD COLUMN IF NOT EXISTS superseded_by UUID REFERENCES regulatory_documents(id) ON DELETE RESTRICT,
+    ADD COLUMN IF NOT EXISTS superseded_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS obsoleted_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    ADD COLUMN IF NOT EXISTS obsolete_reason TEXT,
+    ADD COLUMN IF NOT EXISTS obsoleted_at TIMESTAMPTZ;
+
+-- Existing documents are version 1 of themselves
+UPDATE regulatory_documents SET root_document_id = id WHERE root_document_id IS NULL;
+
+CREATE OR REPLACE FUNCTION set_regulatory_document_root()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.root_document_id IS NULL THEN
+        NEW.root_document_id := NEW.id;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trigger_set_regulatory_document_root ON regulatory_documents;
+CREATE TRIGGER trigger_set_regulatory_document_root
+    BEFORE INSERT ON regulatory_documents
+    FOR EACH ROW
+    EXECUTE FUNCTION set_regulatory_document_root();
+
+ALTER TABLE regulatory_documents ALTER COLUMN root_document_id SET NOT NULL;
+
+ALTER TABLE regulatory_documents ADD CONSTRAINT regulatory_documents_version_check CHECK (
+    (version_number = 1 AND previous_version_id IS NULL)
+    OR (version_number > 1 AND previous_version_id IS NOT NULL AND change_reason IS NOT NULL)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_reg_docs_root_version
+    ON regulatory_documents(root_document_id, version_number);
+
+COMMENT ON COLUMN regulatory_documents.status IS 'draft, pending_approval, approved, rejected, voided, superseded (replaced by an approved later version), obsolete (withdrawn without replacement)';
+COMMENT ON COLUMN regulatory_documents.root_document_id IS 'Version 1 of the document; shared by all its versions';
+COMMENT ON COLUMN regulatory_documents.change_reason IS 'Why this version was created (change control); required from version 2';
+COMMENT ON COLUMN regulatory_document_ledger.operation IS 'generated, signed, approved, rejected, voided, amended, revised, superseded, obsoleted';
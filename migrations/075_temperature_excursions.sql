@@ -0,0 +1,112 @@
+-- GDP Temperature Excursion Recording
+-- Temperature logger data (CSV/JSON exports) attached to shipments (marketplace
+-- transactions) by either party, analysed against the product's storage conditions.
+-- Each run of readings outside the allowed range is recorded as an excursion. The buyer
+-- can open a dispute from an excursion report; the seller accepts or rejects it.
+
+CREATE TABLE IF NOT EXISTS shipment_temperature_logs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    transaction_id UUID NOT NULL REFERENCES transactions(id) ON DELETE CASCADE,
+    uploaded_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    logger_serial VARCHAR(100),
+    source_format VARCHAR(10) NOT NULL CHECK (source_format IN ('csv', 'json')),
+
+    -- Allowed range: parsed from the product's storage requirements, or given on upload
+    range_source VARCHAR(20) NOT NULL CHECK (range_source IN ('product', 'manual')),
+    min_allowed_c DOUBLE PRECISION,
+    max_allowed_c DOUBLE PRECISION,
+
+    reading_count INTEGER NOT NULL CHECK (reading_count >= 2),
+    skipped_rows INTEGER NOT NULL DEFAULT 0,
+    first_reading_at TIMESTAMPTZ NOT NULL,
+    last_reading_at TIMESTAMPTZ NOT NULL,
+    min_temp_c DOUBLE PRECISION NOT NULL,
+    max_temp_c DOUBLE PRECISION NOT NULL,
+    mean_temp_c DOUBLE PRECISION NOT NULL,
+    mean_kinetic_temp_c DOUBLE PRECISION NOT NULL,
+    longest_gap_seconds BIGINT NOT NULL,
+    excursion_count INTEGER NOT NULL DEFAULT 0,
+    total_excursion_seconds BIGINT NOT NULL DEFAULT 0,
+
+    -- [{"recorded_at": ..., "temperature_c": ...}] in time order, °C
+    readings JSONB NOT NULL,
+    notes TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CHECK (min_allowed_c IS NOT NULL OR max_allowed_c IS NOT NULL),
+    CHECK (min_allowed_c IS NULL OR max_allowed_c IS NULL OR min_allowed_c <= max_allowed_c)
+);
+
+CREATE INDEX IF NOT EXISTS idx_temperature_logs_transaction ON shipment_temperature_logs(transaction_id);
+CREATE INDEX IF NOT EXISTS idx_temperature_logs_created ON shipment_temperature_logs(created_at);
+
+CREATE TABLE IF NOT EXISTS shipment_temperature_excursions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    temperature_log_id UUID NOT NULL REFERENCES shipment_temperature_logs(id) ON DELETE CASCADE,
+    transaction_id UUID NOT NULL REFERENCES transactions(id) ON DELETE CASCADE,
+    kind VARCHAR(10) NOT NULL CHECK (kind IN ('above', 'below')),
+    started_at TIMESTAMPTZ NOT NULL,
+    ended_at TIMESTAMPTZ NOT NULL,
+    duration_seconds BIGINT NOT NULL CHECK (duration_seconds >= 0),
+    reading_count INTEGER NOT NULL,
+    extreme_temp_c DOUBLE PRECISION NOT NULL,
+    deviation_c DOUBLE PRECISION NOT NULL,
+    -- Still outside the range at the last reading
+    ongoing BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+CREATE INDEX IF NOT EXISTS idx_temperature_excursions_log ON shipment_temperature_excursions(temperature_log_id);
+CREATE INDEX IF NOT EXISTS idx_temperature_excursions_transaction ON shipment_temperature_excursions(transaction_id);
+
+CREATE TABLE IF NOT EXISTS transaction_disputes (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    transaction_id UUID NOT NULL REFERENCES transactions(id) ON DELETE CASCADE,
+    raised_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    reason VARCHAR(50) NOT NULL CHECK (reason IN ('temperature_excursion')),
+    temperature_log_id UUID REFERENCES shipment_temperature_logs(id) ON DELETE SET NULL,
+    description TEXT NOT NULL,
+    -- Excursion report at the time the dispute was opened
+    evidence JSONB NOT NULL DEFAULT '{}',
+    status VARCHAR(20) NOT NULL DEFAULT 'open'
+        CHECK (status IN ('open', 'accepted', 'rejected', 'withdrawn')),
+    resolution TEXT,
+    resolved_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    resolved_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CHECK (status = 'open' OR (resolution IS NOT NULL AND resolved_at IS NOT NULL))
+);
+
+CREATE INDEX IF NOT EXISTS idx_transaction_disputes_transaction ON transaction_disputes(transaction_id);
+CREATE INDEX IF NOT EXISTS idx_transaction_disputes_status ON transaction_disputes(status);
+
+-- One open dispute per temperature log
+CREATE UNIQUE INDEX IF NOT EXISTS idx_transaction_disputes_open_log
+    ON transaction_disputes(temperature_log_id)
+    WHERE status = 'open';
+
+-- Sellers are notified of disputes, the other party of their resolution
+ALTER TABLE alert_notifications DROP CONSTRAINT IF EXISTS alert_notifications_alert_type_check;
+ALTER TABLE alert_notifications ADD CONSTRAINT alert_notifications_alert_type_check
+CHECK (alert_type IN (
+    'expiry_warning',
+    'expiry_critical',
+    'low_stock',
+    'watchlist_match',
+    'price_drop',
+    'back_in_stock',
+    'new_inquiry',
+    'inquiry_message',
+    'catalog_change',
+    'report_ready',
+    'digest',
+    'announcement',
+    'dispute',
+    'system'
+));
+
+COMMENT ON TABLE shipment_temperature_logs IS 'Temperature logger data of a shipment (marketplace transaction) and its GDP analysis';
+COMMENT ON COLUMN shipment_temperature_logs.mean_kinetic_temp_c IS 'Mean kinetic temperature (ΔH/R = 10000 K)';
+COMMENT ON TABLE shipment_temperature_excursions IS 'Runs of logger readings outside the allowed storage range';
+COMMENT ON TABLE transaction_disputes IS 'Buyer claims against a shipment, e.g. from a temperature excursion report';
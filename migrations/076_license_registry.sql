@@ -0,0 +1,120 @@
+-- License and Certification Registry
+-- Structured records of the licenses a company trades under (wholesale distribution
+-- authorizations, GDP certificates, DEA registrations, ...) with the scanned document
+-- attached. Admins verify uploads. The daily license_expiry_check job sends reminders
+-- ahead of expiry and suspends the listing rights of a company whose verified licenses
+-- of a type have all expired, until a renewed license is verified.
+
+CREATE TABLE IF NOT EXISTS business_licenses (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    license_type VARCHAR(50) NOT NULL CHECK (license_type IN (
+        'wholesale_distribution_authorization',
+        'gdp_certificate',
+        'dea_registration',
+        'state_license',
+        'other'
+    )),
+    license_number VARCHAR(100) NOT NULL,
+    issuing_authority VARCHAR(255) NOT NULL,
+    -- ISO 3166-1 alpha-2
+    country_code CHAR(2),
+    issued_on DATE,
+    -- NULL: does not expire
+    expires_on DATE,
+
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'verified', 'rejected', 'revoked')),
+    reviewed_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    reviewed_at TIMESTAMPTZ,
+    review_notes TEXT,
+
+    document_path TEXT NOT NULL,
+    document_filename VARCHAR(255) NOT NULL,
+    document_content_type VARCHAR(100) NOT NULL,
+    document_size_bytes BIGINT NOT NULL,
+    document_hash VARCHAR(64) NOT NULL,
+
+    -- Smallest reminder threshold (days before expiry) already sent
+    last_reminder_days INTEGER,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CHECK (issued_on IS NULL OR expires_on IS NULL OR issued_on < expires_on),
+    CHECK (status = 'pending' OR reviewed_at IS NOT NULL)
+);
+
+CREATE INDEX IF NOT EXISTS idx_business_licenses_user ON business_licenses(user_id);
+CREATE INDEX IF NOT EXISTS idx_business_licenses_status ON business_licenses(status);
+CREATE INDEX IF NOT EXISTS idx_business_licenses_expiry ON business_licenses(expires_on)
+    WHERE status = 'verified';
+
+-- The same license can't be registered twice, but may be re-uploaded after a rejection
+CREATE UNIQUE INDEX IF NOT EXISTS idx_business_licenses_unique_number
+    ON business_licenses(user_id, license_type, license_number)
+    WHERE status IN ('pending', 'verified');
+
+-- Set while the company may not list inventory (expired licenses)
+ALTER TABLE users ADD COLUMN IF NOT EXISTS listing_suspended_at TIMESTAMPTZ;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS listing_suspension_reason TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_users_listing_suspended ON users(listing_suspended_at)
+    WHERE listing_suspended_at IS NOT NULL;
+
+-- Expiry reminders, review outcomes and listing suspensions
+ALTER TABLE alert_notifications DROP CONSTRAINT IF EXISTS alert_notifications_alert_type_check;
+ALTER TABLE alert_notifications ADD CONSTRAINT alert_notifications_alert_type_check
+CHECK (alert_type IN (
+    'expiry_warning',
+    'expiry_critical',
+    'low_stock',
+    'watchlist_match',
+    'price_drop',
+    'back_in_stock',
+    'new_inquiry',
+    'inquiry_message',
+    'catalog_change',
+    'report_ready',
+    'digest',
+    'announcement',
+    'dispute',
+    'license',
+    'system'
+));
+
+ALTER TABLE alert_processing_log DROP CONSTRAINT IF EXISTS alert_processing_log_run_type_check;
+ALTER TABLE alert_processing_log ADD CONSTRAINT alert_processing_log_run_type_check
+CHECK (run_type IN (
+    'expiry_check',
+    'low_stock_check',
+    'watchlist_check',
+    'watchlist_trigger_check',
+    'scheduled_run',
+    'digest_delivery',
+    'announcement_delivery',
+    'notification_retention',
+    'data_purge',
+    'license_expiry_check'
+));
+
+ALTER TABLE alert_scheduler_jobs DROP CONSTRAINT IF EXISTS alert_scheduler_jobs_job_type_check;
+ALTER TABLE alert_scheduler_jobs ADD CONSTRAINT alert_scheduler_jobs_job_type_check
+CHECK (job_type IN (
+    'expiry_check',
+    'low_stock_check',
+    'watchlist_check',
+    'watchlist_trigger_check',
+    'digest_delivery',
+    'announcement_delivery',
+    'notification_retention',
+    'data_purge',
+    'license_expiry_check'
+));
+
+INSERT INTO alert_scheduler_jobs (job_type, interval_seconds, lease_seconds)
+VALUES ('license_expiry_check', 86400, 1800)
+ON CONFLICT (job_type) DO NOTHING;
+
+COMMENT ON TABLE business_licenses IS 'Licenses and certifications a company trades under, verified by admins';
+COMMENT ON COLUMN business_licenses.last_reminder_days IS 'Smallest expiry reminder threshold (days) already sent for the license';
+COMMENT ON COLUMN users.listing_suspended_at IS 'Listing rights suspended (expired licenses) since; NULL when allowed to list';
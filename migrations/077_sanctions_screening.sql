@@ -0,0 +1,133 @@
+-- Sanctions and Denied-Party Screening
+-- Local copies of the OFAC SDN list and the EU consolidated financial sanctions list,
+-- refreshed daily by the sanctions_list_sync job. Users are screened by name at
+-- registration and both parties before a transaction completes. Every screening is
+-- stored with its match scores; potential hits flag the transaction for admin review,
+-- strong hits block its completion until an admin clears them as a false positive.
+
+CREATE TABLE IF NOT EXISTS sanctions_list_entries (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    source VARCHAR(30) NOT NULL CHECK (source IN ('ofac_sdn', 'eu_consolidated')),
+    -- SDN entity number / EU logical id
+    external_id VARCHAR(50) NOT NULL,
+    entity_type VARCHAR(20) NOT NULL DEFAULT 'unknown'
+        CHECK (entity_type IN ('individual', 'entity', 'vessel', 'aircraft', 'unknown')),
+    primary_name TEXT NOT NULL,
+    -- Primary name and aliases, normalized for matching
+    names TEXT[] NOT NULL,
+    -- First characters of every name token, for candidate lookup
+    name_keys TEXT[] NOT NULL,
+    programs TEXT,
+    countries TEXT[] NOT NULL DEFAULT '{}',
+    remarks TEXT,
+    synced_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    UNIQUE (source, external_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_sanctions_entries_keys ON sanctions_list_entries USING GIN (name_keys);
+
+CREATE TABLE IF NOT EXISTS sanctions_list_syncs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    source VARCHAR(30) NOT NULL CHECK (source IN ('ofac_sdn', 'eu_consolidated')),
+    status VARCHAR(20) NOT NULL DEFAULT 'running' CHECK (status IN ('running', 'completed', 'failed')),
+    entry_count INTEGER,
+    error_message TEXT,
+    started_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_sanctions_syncs_source ON sanctions_list_syncs(source, started_at DESC);
+
+-- One sync per source at a time
+CREATE UNIQUE INDEX IF NOT EXISTS idx_sanctions_syncs_running
+    ON sanctions_list_syncs(source)
+    WHERE status = 'running';
+
+CREATE TABLE IF NOT EXISTS sanctions_screenings (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    transaction_id UUID REFERENCES transactions(id) ON DELETE SET NULL,
+    trigger VARCHAR(20) NOT NULL CHECK (trigger IN ('registration', 'transaction', 'manual')),
+    screened_names TEXT[] NOT NULL,
+    country_code VARCHAR(2),
+    top_score DOUBLE PRECISION NOT NULL DEFAULT 0,
+    -- clear: no hit; flagged: potential hit, reviewed afterwards; blocked: strong hit,
+    -- the transaction can't complete until cleared
+    outcome VARCHAR(20) NOT NULL CHECK (outcome IN ('clear', 'flagged', 'blocked')),
+    status VARCHAR(20) NOT NULL
+        CHECK (status IN ('clear', 'pending_review', 'false_positive', 'confirmed_match')),
+    -- [{"entry_id", "source", "external_id", "primary_name", "matched_name", "screened_name", "score", "programs"}]
+    matches JSONB NOT NULL DEFAULT '[]',
+    -- Latest completed list sync at screening time; NULL: no list was available
+    lists_synced_at TIMESTAMPTZ,
+    reviewed_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    reviewed_at TIMESTAMPTZ,
+    review_notes TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CHECK (status IN ('clear', 'pending_review') OR reviewed_at IS NOT NULL)
+);
+
+CREATE INDEX IF NOT EXISTS idx_sanctions_screenings_user ON sanctions_screenings(user_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_sanctions_screenings_transaction ON sanctions_screenings(transaction_id);
+CREATE INDEX IF NOT EXISTS idx_sanctions_screenings_pending ON sanctions_screenings(created_at)
+    WHERE status = 'pending_review';
+
+-- List entries an admin cleared as false positives for a user; not reported again
+CREATE TABLE IF NOT EXISTS sanctions_cleared_matches (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    entry_id UUID NOT NULL REFERENCES sanctions_list_entries(id) ON DELETE CASCADE,
+    screening_id UUID REFERENCES sanctions_screenings(id) ON DELETE SET NULL,
+    cleared_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    cleared_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, entry_id)
+);
+
+-- Result of the latest screening of the parties; NULL: not screened yet
+ALTER TABLE transactions ADD COLUMN IF NOT EXISTS sanctions_status VARCHAR(20)
+    CHECK (sanctions_status IN ('clear', 'flagged', 'blocked'));
+
+-- Set when an admin confirms a sanctions match; the user can't complete transactions
+ALTER TABLE users ADD COLUMN IF NOT EXISTS sanctions_blocked_at TIMESTAMPTZ;
+
+ALTER TABLE alert_processing_log DROP CONSTRAINT IF EXISTS alert_processing_log_run_type_check;
+ALTER TABLE alert_processing_log ADD CONSTRAINT alert_processing_log_run_type_check
+CHECK (run_type IN (
+    'expiry_check',
+    'low_stock_check',
+    'watchlist_check',
+    'watchlist_trigger_check',
+    'scheduled_run',
+    'digest_delivery',
+    'announcement_delivery',
+    'notification_retention',
+    'data_purge',
+    'license_expiry_check',
+    'sanctions_list_sync'
+));
+
+ALTER TABLE alert_scheduler_jobs DROP CONSTRAINT IF EXISTS alert_scheduler_jobs_job_type_check;
+ALTER TABLE alert_scheduler_jobs ADD CONSTRAINT alert_scheduler_jobs_job_type_check
+CHECK (job_type IN (
+    'expiry_check',
+    'low_stock_check',
+    'watchlist_check',
+    'watchlist_trigger_check',
+    'digest_delivery',
+    'announcement_delivery',
+    'notification_retention',
+    'data_purge',
+    'license_expiry_check',
+    'sanctions_list_sync'
+));
+
+INSERT INTO alert_scheduler_jobs (job_type, interval_seconds, lease_seconds)
+VALUES ('sanctions_list_sync', 86400, 3600)
+ON CONFLICT (job_type) DO NOTHING;
+
+COMMENT ON TABLE sanctions_list_entries IS 'OFAC SDN and EU consolidated sanctions list entries, replaced on every list sync';
+COMMENT ON TABLE sanctions_screenings IS 'Name screenings of users against the sanctions lists, with match scores and admin review';
+COMMENT ON TABLE sanctions_cleared_matches IS 'Sanctions list entries cleared as false positives for a user';
+COMMENT ON COLUMN transactions.sanctions_status IS 'Latest sanctions screening outcome of the parties (clear, flagged, blocked)';
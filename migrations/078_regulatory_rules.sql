@@ -0,0 +1,113 @@
+-- Country-Specific Regulatory Rules
+-- Admin-configured rules on what a trade needs in a jurisdiction: the product being
+-- authorized in the buyer's country, a license the buyer or seller must hold (import
+-- license, DEA registration, narcotics export permit, ...) or a notice to act on.
+-- Rules are evaluated when an inquiry or transaction is created; the parties get the
+-- list of requirements, and unmet requirements of blocking rules refuse the trade.
+
+CREATE TABLE IF NOT EXISTS regulatory_rules (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(200) NOT NULL,
+    description TEXT,
+
+    -- Conditions; empty arrays match anything
+    seller_countries TEXT[] NOT NULL DEFAULT '{}',
+    buyer_countries TEXT[] NOT NULL DEFAULT '{}',
+    -- any; domestic: same country; cross_border: different countries
+    scope VARCHAR(20) NOT NULL DEFAULT 'any' CHECK (scope IN ('any', 'domestic', 'cross_border')),
+    product_domains TEXT[] NOT NULL DEFAULT '{}',
+    -- DEA schedules (CI-CV) from the openFDA catalog; non-empty: controlled substances only
+    dea_schedules TEXT[] NOT NULL DEFAULT '{}',
+
+    -- What the trade needs
+    requirement_type VARCHAR(30) NOT NULL
+        CHECK (requirement_type IN ('product_authorization', 'buyer_license', 'seller_license', 'notice')),
+    -- License type for buyer_license / seller_license (see business_licenses)
+    license_type VARCHAR(50),
+    -- block: unmet requirement refuses the trade; warn: listed as an action item
+    enforcement VARCHAR(10) NOT NULL DEFAULT 'warn' CHECK (enforcement IN ('block', 'warn')),
+    -- Shown to the parties, e.g. where to apply for the permit
+    guidance TEXT,
+
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    updated_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CHECK ((requirement_type IN ('buyer_license', 'seller_license')) = (license_type IS NOT NULL)),
+    CHECK (requirement_type <> 'notice' OR enforcement = 'warn')
+);
+
+CREATE INDEX IF NOT EXISTS idx_regulatory_rules_active ON regulatory_rules(is_active);
+
+-- Rule evaluations of inquiries and transactions, including refused ones
+CREATE TABLE IF NOT EXISTS regulatory_rule_evaluations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    context VARCHAR(20) NOT NULL CHECK (context IN ('inquiry', 'transaction')),
+    inquiry_id UUID REFERENCES inquiries(id) ON DELETE SET NULL,
+    transaction_id UUID REFERENCES transactions(id) ON DELETE SET NULL,
+    inventory_id UUID REFERENCES inventory(id) ON DELETE SET NULL,
+    buyer_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    seller_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    buyer_country VARCHAR(2),
+    seller_country VARCHAR(2),
+    blocked BOOLEAN NOT NULL,
+    -- [{"rule_id", "rule_name", "requirement_type", "party", "status", "blocking", "message", "guidance"}]
+    requirements JSONB NOT NULL DEFAULT '[]',
+    evaluated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_regulatory_evaluations_inquiry ON regulatory_rule_evaluations(inquiry_id, evaluated_at DESC);
+CREATE INDEX IF NOT EXISTS idx_regulatory_evaluations_transaction ON regulatory_rule_evaluations(transaction_id, evaluated_at DESC);
+CREATE INDEX IF NOT EXISTS idx_regulatory_evaluations_blocked ON regulatory_rule_evaluations(evaluated_at DESC)
+    WHERE blocked;
+
+-- Import licenses and narcotics permits are registered like other licenses
+ALTER TABLE business_licenses DROP CONSTRAINT IF EXISTS business_licenses_license_type_check;
+ALTER TABLE business_licenses ADD CONSTRAINT business_licenses_license_type_check
+CHECK (license_type IN (
+    'wholesale_distribution_authorization',
+    'gdp_certificate',
+    'dea_registration',
+    'state_license',
+    'import_license',
+    'narcotics_permit',
+    'other'
+));
+
+-- Baseline rules; admins adjust or deactivate them
+INSERT INTO regulatory_rules (name, description, buyer_countries, dea_schedules, requirement_type, license_type, enforcement, guidance)
+VALUES (
+    'DEA registration for controlled substances (US buyers)',
+    'Buyers of Schedule I-V controlled substances in the US must be DEA registered (21 CFR 1301.11).',
+    '{US}', '{CI,CII,CIII,CIV,CV}', 'buyer_license', 'dea_registration', 'block',
+    'Register your DEA registration in the license registry; trading resumes once it is verified.'
+);
+
+INSERT INTO regulatory_rules (name, description, scope, dea_schedules, requirement_type, license_type, enforcement, guidance)
+VALUES (
+    'Narcotics export permit for cross-border controlled substances',
+    'Exporting controlled substances requires an export permit from the exporting country''s competent authority.',
+    'cross_border', '{CI,CII,CIII,CIV,CV}', 'seller_license', 'narcotics_permit', 'block',
+    'The seller must register a verified narcotics export permit before the trade can proceed.'
+);
+
+INSERT INTO regulatory_rules (name, description, scope, requirement_type, license_type, enforcement, guidance)
+VALUES (
+    'Import license for cross-border purchases',
+    'Buyers importing medicinal products usually need an import license or manufacturing/import authorization.',
+    'cross_border', 'buyer_license', 'import_license', 'warn',
+    'Register your import license in the license registry.'
+);
+
+INSERT INTO regulatory_rules (name, description, scope, product_domains, requirement_type, enforcement, guidance)
+VALUES (
+    'Product authorized in the buyer''s country',
+    'Medicinal products may only be placed on the market where they hold a marketing authorization.',
+    'cross_border', '{human_drug,veterinary}', 'product_authorization', 'warn',
+    'Confirm the marketing authorization in the buyer''s country, or the applicable import exemption.'
+);
+
+COMMENT ON TABLE regulatory_rules IS 'Admin-configured jurisdiction rules evaluated when inquiries and transactions are created';
+COMMENT ON TABLE regulatory_rule_evaluations IS 'Regulatory rule evaluations of inquiries and transactions with their requirement lists';
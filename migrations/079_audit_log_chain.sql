@@ -0,0 +1,223 @@
+-- Audit Log Integrity Chain
+-- Every audit_logs row is linked into a hash chain: entry_hash = sha256(prev_hash ||
+-- content hash of the row), assigned on insert under a lock on the chain head, so any
+-- modified, removed or re-ordered entry breaks the chain. Rows deleted legitimately
+-- (retention) leave a tombstone with their hashes so the chain still verifies across
+-- them. The head is anchored periodically (audit_chain_anchor job), optionally to an
+-- external endpoint, so truncating the tail is detected as well.
+
+ALTER TABLE audit_logs ADD COLUMN IF NOT EXISTS chain_seq BIGINT;
+ALTER TABLE audit_logs ADD COLUMN IF NOT EXISTS prev_hash VARCHAR(64);
+ALTER TABLE audit_logs ADD COLUMN IF NOT EXISTS entry_hash VARCHAR(64);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_audit_logs_chain_seq ON audit_logs(chain_seq);
+
+-- Canonical content hash of an entry; timestamps in UTC so the session time zone
+-- doesn't change it. Chain columns are not part of the content.
+CREATE OR REPLACE FUNCTION audit_log_content_hash(l audit_logs)
+RETURNS TEXT AS $$
+    SELECT encode(sha256(convert_to(jsonb_build_array(
+        l.id,
+        l.event_id,
+        l.event_type,
+        l.event_category,
+        l.severity,
+        l.actor_user_id,
+        l.actor_type,
+        l.actor_identifier,
+        l.resource_type,
+        l.resource_id,
+        l.resource_name,
+        l.action,
+        l.action_result,
+        l.event_data,
+        l.ip_address::TEXT,
+        l.user_agent,
+        l.request_id,
+        l.session_id,
+        l.changes_summary,
+        l.old_values,
+        l.new_values,
+        to_char(l.retention_until AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS.US'),
+        l.is_pii_access,
+        l.compliance_tags,
+        to_char(l.created_at AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS.US')
+    )::TEXT, 'UTF8')), 'hex')
+$$ LANGUAGE sql STABLE;
+
+CREATE OR REPLACE FUNCTION audit_chain_link(prev_hash TEXT, content_hash TEXT)
+RETURNS TEXT AS $$
+    SELECT encode(sha256(convert_to(prev_hash || content_hash, 'UTF8')), 'hex')
+$$ LANGUAGE sql IMMUTABLE;
+
+-- Latest link; its row lock serializes audit log inserts
+CREATE TABLE IF NOT EXISTS audit_chain_head (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    chain_seq BIGINT NOT NULL,
+    entry_hash VARCHAR(64) NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Chained entries deleted by retention, keeping the chain verifiable across them
+CREATE TABLE IF NOT EXISTS audit_chain_tombstones (
+    chain_seq BIGINT PRIMARY KEY,
+    prev_hash VARCHAR(64) NOT NULL,
+    entry_hash VARCHAR(64) NOT NULL,
+    audit_log_id BIGINT NOT NULL,
+    entry_created_at TIMESTAMPTZ NOT NULL,
+    deleted_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS audit_chain_anchors (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    chain_seq BIGINT NOT NULL,
+    entry_hash VARCHAR(64) NOT NULL,
+    -- Segment verified before anchoring: (previous anchor, chain_seq]
+    verified_from_seq BIGINT,
+    issues_found INTEGER NOT NULL DEFAULT 0,
+    -- External anchoring (AUDIT_ANCHOR_URL); NULL when not configured
+    published_at TIMESTAMPTZ,
+    publish_error TEXT,
+    anchored_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    anchored_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_chain_anchors_seq ON audit_chain_anchors(chain_seq DESC);
+
+-- Link existing entries in insert order
+ALTER TABLE audit_logs DISABLE TRIGGER audit_log_immutable;
+
+DO $$
+DECLARE
+    entry audit_logs;
+    seq BIGINT := 0;
+    link TEXT := repeat('0', 64);
+    next_link TEXT;
+BEGIN
+    FOR entry IN SELECT * FROM audit_logs WHERE chain_seq IS NULL ORDER BY id LOOP
+        seq := seq + 1;
+        next_link := audit_chain_link(link, audit_log_content_hash(entry));
+        UPDATE audit_logs SET chain_seq = seq, prev_hash = link, entry_hash = next_link WHERE id = entry.id;
+        link := next_link;
+    END LOOP;
+
+    INSERT INTO audit_chain_head (id, chain_seq, entry_hash) VALUES (TRUE, seq, link)
+    ON CONFLICT (id) DO NOTHING;
+END $$;
+
+ALTER TABLE audit_logs ENABLE TRIGGER audit_log_immutable;
+
+CREATE OR REPLACE FUNCTION chain_audit_log()
+RETURNS TRIGGER AS $$
+DECLARE
+    head audit_chain_head;
+BEGIN
+    SELECT * INTO head FROM audit_chain_head WHERE id FOR UPDATE;
+
+    NEW.chain_seq := head.chain_seq + 1;
+    NEW.prev_hash := head.entry_hash;
+    NEW.entry_hash := audit_chain_link(NEW.prev_hash, audit_log_content_hash(NEW));
+
+    UPDATE audit_chain_head
+    SET chain_seq = NEW.chain_seq, entry_hash = NEW.entry_hash, updated_at = NOW()
+    WHERE id;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+-- BEFORE triggers fire in name order; this one must run after set_retention_policy
+DROP TRIGGER IF EXISTS zz_audit_log_chain ON audit_logs;
+CREATE TRIGGER zz_audit_log_chain
+    BEFORE INSERT ON audit_logs
+    FOR EACH ROW
+    EXECUTE FUNCTION chain_audit_log();
+
+-- Same rules as before (see 072); permitted deletes of chained entries leave a tombstone
+CREATE OR REPLACE FUNCTION prevent_audit_log_modification()
+RETURNS TRIGGER AS $$
+DECLARE
+    purge_before TIMESTAMPTZ;
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        purge_before := NULLIF(current_setting('atlas.retention_purge_before', TRUE), '')::TIMESTAMPTZ;
+        IF NOT (purge_before IS NOT NULL AND OLD.created_at < purge_before) THEN
+            -- Only allow deletion of logs past retention period
+            IF OLD.retention_until IS NULL OR OLD.retention_until > NOW() THEN
+                RAISE EXCEPTION 'Audit logs cannot be deleted before retention period expires';
+            END IF;
+        END IF;
+
+        IF OLD.chain_seq IS NOT NULL THEN
+            INSERT INTO audit_chain_tombstones (chain_seq, prev_hash, entry_hash, audit_log_id, entry_created_at)
+            VALUES (OLD.chain_seq, OLD.prev_hash, OLD.entry_hash, OLD.id, OLD.created_at);
+        END IF;
+        RETURN OLD;
+    ELSIF TG_OP = 'UPDATE' THEN
+        -- Audit logs are immutable - no updates allowed
+        RAISE EXCEPTION 'Audit logs are immutable and cannot be updated';
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Tombstones and anchors are append-only
+CREATE OR REPLACE FUNCTION prevent_audit_chain_modification()
+RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'Audit chain records are append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS audit_chain_tombstones_immutable ON audit_chain_tombstones;
+CREATE TRIGGER audit_chain_tombstones_immutable
+    BEFORE UPDATE OR DELETE ON audit_chain_tombstones
+    FOR EACH ROW
+    EXECUTE FUNCTION prevent_audit_chain_modification();
+
+DROP TRIGGER IF EXISTS audit_chain_anchors_immutable ON audit_chain_anchors;
+CREATE TRIGGER audit_chain_anchors_immutable
+    BEFORE UPDATE OR DELETE ON audit_chain_anchors
+    FOR EACH ROW
+    EXECUTE FUNCTION prevent_audit_chain_modification();
+
+ALTER TABLE alert_processing_log DROP CONSTRAINT IF EXISTS alert_processing_log_run_type_check;
+ALTER TABLE alert_processing_log ADD CONSTRAINT alert_processing_log_run_type_check
+CHECK (run_type IN (
+    'expiry_check',
+    'low_stock_check',
+    'watchlist_check',
+    'watchlist_trigger_check',
+    'scheduled_run',
+    'digest_delivery',
+    'announcement_delivery',
+    'notification_retention',
+    'data_purge',
+    'license_expiry_check',
+    'sanctions_list_sync',
+    'audit_chain_anchor'
+));
+
+ALTER TABLE alert_scheduler_jobs DROP CONSTRAINT IF EXISTS alert_scheduler_jobs_job_type_check;
+ALTER TABLE alert_scheduler_jobs ADD CONSTRAINT alert_scheduler_jobs_job_type_check
+CHECK (job_type IN (
+    'expiry_check',
+    'low_stock_check',
+    'watchlist_check',
+    'watchlist_trigger_check',
+    'digest_delivery',
+    'announcement_delivery',
+    'notification_retention',
+    'data_purge',
+    'license_expiry_check',
+    'sanctions_list_sync',
+    'audit_chain_anchor'
+));
+
+INSERT INTO alert_scheduler_jobs (job_type, interval_seconds, lease_seconds)
+VALUES ('audit_chain_anchor', 3600, 1800)
+ON CONFLICT (job_type) DO NOTHING;
+
+COMMENT ON COLUMN audit_logs.entry_hash IS 'sha256(prev_hash || content hash); links the entry into the audit chain';
+COMMENT ON TABLE audit_chain_tombstones IS 'Hashes of audit log entries deleted by retention, keeping the chain verifiable';
+COMMENT ON TABLE audit_chain_anchors IS 'Periodic snapshots of the audit chain head, optionally published externally';
@@ -0,0 +1,46 @@
+-- Regulatory Document Batches
+-- A seller generates one document type (DSCSA transaction data, or an AI-generated
+-- CoA/GDP/GMP document) for a filtered set of their transactions in one background
+-- job. Each transaction is an item with its own result; the generated documents are
+-- downloadable together as a ZIP once the batch has finished.
+
+CREATE TABLE IF NOT EXISTS regulatory_document_batches (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    document_type VARCHAR(10) NOT NULL CHECK (document_type IN ('DSCSA', 'COA', 'GDP', 'GMP')),
+    -- Transaction filter the batch was created with
+    filters JSONB NOT NULL DEFAULT '{}',
+    status VARCHAR(30) NOT NULL DEFAULT 'queued'
+        CHECK (status IN ('queued', 'running', 'completed', 'completed_with_errors', 'failed')),
+    total_items INTEGER NOT NULL,
+    processed_items INTEGER NOT NULL DEFAULT 0,
+    succeeded_items INTEGER NOT NULL DEFAULT 0,
+    failed_items INTEGER NOT NULL DEFAULT 0,
+    error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    started_at TIMESTAMPTZ,
+    completed_at TIMESTAMPTZ,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_regulatory_document_batches_user
+    ON regulatory_document_batches(user_id, created_at DESC);
+
+CREATE TABLE IF NOT EXISTS regulatory_document_batch_items (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    batch_id UUID NOT NULL REFERENCES regulatory_document_batches(id) ON DELETE CASCADE,
+    transaction_id UUID NOT NULL REFERENCES transactions(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'succeeded', 'failed')),
+    -- Documents generated (or already existing, for DSCSA) for the transaction
+    document_ids UUID[] NOT NULL DEFAULT '{}',
+    error TEXT,
+    processed_at TIMESTAMPTZ,
+    UNIQUE (batch_id, transaction_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_regulatory_document_batch_items_batch
+    ON regulatory_document_batch_items(batch_id, position);
+
+COMMENT ON TABLE regulatory_document_batches IS 'Bulk generation of one regulatory document type for a set of transactions';
+COMMENT ON TABLE regulatory_document_batch_items IS 'Per-transaction result of a regulatory document batch';
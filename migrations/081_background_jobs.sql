@@ -0,0 +1,83 @@
+-- Background Jobs
+-- Persistent queue for work that used to be spawned fire-and-forget inside the API process
+-- and was lost on restart: manual ERP syncs, AI imports, OpenFDA drug catalog syncs,
+-- regulatory document batches and manual sanctions list syncs.
+-- Every instance runs a worker. It claims a due job with FOR UPDATE SKIP LOCKED and holds
+-- a lease that it renews while the job runs; when an instance dies its lease expires and
+-- another instance picks the job up again. Failed attempts are retried with exponential
+-- backoff until max_attempts is reached.
+-- Concurrency across all instances, attempts and lease length are per job type in
+-- background_job_types and can be tuned here without a redeploy.
+
+CREATE TABLE IF NOT EXISTS background_job_types (
+    job_type VARCHAR(50) PRIMARY KEY CHECK (job_type IN (
+        'erp_sync',
+        'ai_import',
+        'openfda_sync',
+        'document_batch',
+        'sanctions_list_sync'
+    )),
+    max_concurrency INTEGER NOT NULL CHECK (max_concurrency >= 1),
+    max_attempts INTEGER NOT NULL CHECK (max_attempts >= 1),
+    lease_seconds INTEGER NOT NULL CHECK (lease_seconds >= 30),
+    is_enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+INSERT INTO background_job_types (job_type, max_concurrency, max_attempts, lease_seconds) VALUES
+    ('erp_sync', 4, 3, 300),
+    ('ai_import', 2, 3, 300),
+    ('openfda_sync', 1, 3, 600),
+    ('document_batch', 2, 3, 300),
+    ('sanctions_list_sync', 1, 2, 600)
+ON CONFLICT (job_type) DO NOTHING;
+
+CREATE TABLE IF NOT EXISTS background_jobs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    job_type VARCHAR(50) NOT NULL REFERENCES background_job_types(job_type),
+    payload JSONB NOT NULL DEFAULT '{}'::jsonb,
+    status VARCHAR(20) NOT NULL DEFAULT 'queued'
+        CHECK (status IN ('queued', 'running', 'succeeded', 'failed', 'cancelled')),
+
+    -- At most one queued or running job per key (e.g. one sync per ERP connection)
+    dedupe_key VARCHAR(255),
+
+    attempts INTEGER NOT NULL DEFAULT 0,
+    max_attempts INTEGER NOT NULL CHECK (max_attempts >= 1),
+    -- Not claimed before this time (delayed jobs and retry backoff)
+    run_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    -- Current lease (instance id of the worker)
+    lease_owner VARCHAR(255),
+    lease_expires_at TIMESTAMPTZ,
+
+    -- Running jobs finish their attempt and are then cancelled instead of retried
+    cancel_requested BOOLEAN NOT NULL DEFAULT FALSE,
+
+    last_error TEXT,
+    result JSONB,
+
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    started_at TIMESTAMPTZ,
+    completed_at TIMESTAMPTZ,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_background_jobs_due
+    ON background_jobs(job_type, run_at)
+    WHERE status = 'queued';
+
+CREATE INDEX IF NOT EXISTS idx_background_jobs_running
+    ON background_jobs(job_type, lease_expires_at)
+    WHERE status = 'running';
+
+CREATE INDEX IF NOT EXISTS idx_background_jobs_created
+    ON background_jobs(created_at DESC);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_background_jobs_dedupe
+    ON background_jobs(dedupe_key)
+    WHERE dedupe_key IS NOT NULL AND status IN ('queued', 'running');
+
+COMMENT ON TABLE background_jobs IS 'Persistent background job queue with leases, retries and per-type concurrency limits';
+COMMENT ON TABLE background_job_types IS 'Concurrency, retry and lease settings per background job type';
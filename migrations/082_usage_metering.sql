@@ -0,0 +1,106 @@
+-- Usage Metering and Billing Statements
+-- Billable usage per account, aggregated per day:
+-- - transactions, ai_calls and api_calls are counters; a day holds the sum
+-- - listings (available inventory) and erp_connections (not disabled) are gauges,
+--   sampled hourly; a day holds its peak
+-- A month bills the counters' sum and the gauges' peak beyond the plan's included
+-- quantities, plus the plan's base fee. Statements are generated once per account and
+-- month after the month has ended.
+-- Billing plans share their names with the AI quota tiers; assigning a plan moves the
+-- account to the AI tier of the same name.
+
+CREATE TABLE IF NOT EXISTS billing_plans (
+    plan VARCHAR(20) PRIMARY KEY,
+    display_name VARCHAR(50) NOT NULL,
+    monthly_base_fee_usd DECIMAL(10, 2) NOT NULL DEFAULT 0 CHECK (monthly_base_fee_usd >= 0),
+    -- Inactive plans can't be assigned; accounts already on them keep them
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS billing_plan_meters (
+    plan VARCHAR(20) NOT NULL REFERENCES billing_plans(plan) ON DELETE CASCADE,
+    meter VARCHAR(30) NOT NULL CHECK (meter IN (
+        'listings',
+        'transactions',
+        'ai_calls',
+        'erp_connections',
+        'api_calls'
+    )),
+    included_quantity BIGINT NOT NULL DEFAULT 0 CHECK (included_quantity >= 0),
+    -- Price per unit beyond the included quantity; NULL bills nothing beyond it
+    overage_unit_price_usd DECIMAL(12, 6) CHECK (overage_unit_price_usd >= 0),
+    PRIMARY KEY (plan, meter)
+);
+
+INSERT INTO billing_plans (plan, display_name, monthly_base_fee_usd) VALUES
+    ('free', 'Free', 0),
+    ('pro', 'Pro', 199.00),
+    ('enterprise', 'Enterprise', 999.00)
+ON CONFLICT (plan) DO NOTHING;
+
+INSERT INTO billing_plan_meters (plan, meter, included_quantity, overage_unit_price_usd) VALUES
+    ('free', 'listings', 25, NULL),
+    ('free', 'transactions', 20, NULL),
+    ('free', 'ai_calls', 100, NULL),
+    ('free', 'erp_connections', 0, NULL),
+    ('free', 'api_calls', 10000, NULL),
+    ('pro', 'listings', 500, 0.10),
+    ('pro', 'transactions', 200, 0.50),
+    ('pro', 'ai_calls', 2000, 0.02),
+    ('pro', 'erp_connections', 2, 25.00),
+    ('pro', 'api_calls', 100000, 0.0001),
+    ('enterprise', 'listings', 10000, 0.05),
+    ('enterprise', 'transactions', 5000, 0.25),
+    ('enterprise', 'ai_calls', 20000, 0.015),
+    ('enterprise', 'erp_connections', 20, 15.00),
+    ('enterprise', 'api_calls', 2000000, 0.00005)
+ON CONFLICT (plan, meter) DO NOTHING;
+
+-- Accounts without a row are on the free plan
+CREATE TABLE IF NOT EXISTS billing_accounts (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    plan VARCHAR(20) NOT NULL DEFAULT 'free' REFERENCES billing_plans(plan),
+    notes TEXT,
+    assigned_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    assigned_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS usage_daily (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    meter VARCHAR(30) NOT NULL CHECK (meter IN (
+        'listings',
+        'transactions',
+        'ai_calls',
+        'erp_connections',
+        'api_calls'
+    )),
+    -- UTC day
+    usage_date DATE NOT NULL,
+    quantity BIGINT NOT NULL DEFAULT 0 CHECK (quantity >= 0),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, meter, usage_date)
+);
+
+CREATE INDEX IF NOT EXISTS idx_usage_daily_date ON usage_daily(usage_date);
+
+CREATE TABLE IF NOT EXISTS billing_statements (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- Calendar month, end exclusive
+    period_start DATE NOT NULL,
+    period_end DATE NOT NULL,
+    plan VARCHAR(20) NOT NULL,
+    base_fee_usd DECIMAL(12, 2) NOT NULL,
+    usage_fee_usd DECIMAL(12, 2) NOT NULL,
+    total_usd DECIMAL(12, 2) NOT NULL,
+    -- One entry per meter: quantity, included, billable, unit price, amount
+    line_items JSONB NOT NULL DEFAULT '[]'::jsonb,
+    generated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (user_id, period_start)
+);
+
+CREATE INDEX IF NOT EXISTS idx_billing_statements_period ON billing_statements(period_start);
+
+COMMENT ON TABLE usage_daily IS 'Billable usage per account, meter and UTC day (counter sums, gauge peaks)';
+COMMENT ON TABLE billing_statements IS 'Monthly billing statements; one per account and calendar month';
@@ -0,0 +1,53 @@
+-- User Suspensions and Listing Takedowns
+-- A suspended company can't sign in, its sessions are revoked and its listings are
+-- hidden from the marketplace. Suspensions may be indefinite or end automatically at
+-- suspended_until, after which the account is reinstated by the suspension scheduler.
+-- Listings can also be taken down individually (in bulk) without suspending the owner.
+-- Unlike listing_suspended_at (license-driven, see 076), these are set by admins only.
+
+ALTER TABLE users ADD COLUMN IF NOT EXISTS suspended_at TIMESTAMPTZ;
+-- NULL while suspended indefinitely
+ALTER TABLE users ADD COLUMN IF NOT EXISTS suspended_until TIMESTAMPTZ;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS suspension_reason TEXT;
+-- Tokens issued up to this moment are rejected; set on suspension
+ALTER TABLE users ADD COLUMN IF NOT EXISTS sessions_revoked_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_users_suspended ON users(suspended_until)
+    WHERE suspended_at IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_users_sessions_revoked ON users(sessions_revoked_at)
+    WHERE sessions_revoked_at IS NOT NULL;
+
+-- Every suspension and how it ended
+CREATE TABLE IF NOT EXISTS user_suspensions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    reason TEXT NOT NULL,
+    suspended_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    suspended_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- NULL for indefinite suspensions
+    ends_at TIMESTAMPTZ,
+    lifted_at TIMESTAMPTZ,
+    -- NULL when lifted automatically at ends_at
+    lifted_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    lift_notes TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_suspensions_user ON user_suspensions(user_id, suspended_at DESC);
+
+-- At most one open suspension per user
+CREATE UNIQUE INDEX IF NOT EXISTS idx_user_suspensions_open
+    ON user_suspensions(user_id)
+    WHERE lifted_at IS NULL;
+
+-- Listings taken down by an admin stay hidden until restored
+ALTER TABLE inventory ADD COLUMN IF NOT EXISTS taken_down_at TIMESTAMPTZ;
+ALTER TABLE inventory ADD COLUMN IF NOT EXISTS takedown_reason TEXT;
+ALTER TABLE inventory ADD COLUMN IF NOT EXISTS taken_down_by UUID REFERENCES users(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_inventory_taken_down ON inventory(user_id)
+    WHERE taken_down_at IS NOT NULL;
+
+COMMENT ON COLUMN users.suspended_at IS 'Account suspended by an admin since; NULL when active';
+COMMENT ON COLUMN users.sessions_revoked_at IS 'Tokens issued at or before this time are rejected';
+COMMENT ON COLUMN inventory.taken_down_at IS 'Listing taken down by an admin since; hidden from the marketplace';
+COMMENT ON TABLE user_suspensions IS 'History of admin account suspensions and their reinstatement';
@@ -0,0 +1,48 @@
+-- Runtime Settings
+-- Operational settings (rate limits, sync intervals, quota defaults, CORS origins) that
+-- superadmins can change without editing the environment and restarting. A row overrides
+-- the setting's default (its environment variable, or the built-in value); deleting the
+-- row restores the default. The known keys, their types and bounds live in the
+-- application (see models/settings.rs), which validates every write.
+-- Every change is announced on the 'atlas_settings' NOTIFY channel with the key, so
+-- every API instance reloads its cached settings on commit.
+
+CREATE TABLE IF NOT EXISTS settings (
+    key VARCHAR(100) PRIMARY KEY,
+    value JSONB NOT NULL,
+    updated_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Every change, including resets to the default (new_value NULL)
+CREATE TABLE IF NOT EXISTS settings_history (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    key VARCHAR(100) NOT NULL,
+    old_value JSONB,
+    new_value JSONB,
+    changed_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_settings_history_key ON settings_history(key, changed_at DESC);
+
+CREATE OR REPLACE FUNCTION notify_settings_change()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        PERFORM pg_notify('atlas_settings', OLD.key);
+    ELSE
+        PERFORM pg_notify('atlas_settings', NEW.key);
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trigger_settings_change ON settings;
+CREATE TRIGGER trigger_settings_change
+    AFTER INSERT OR UPDATE OR DELETE ON settings
+    FOR EACH ROW
+    EXECUTE FUNCTION notify_settings_change();
+
+COMMENT ON TABLE settings IS 'Runtime overrides of operational settings; absent keys use their defaults';
+COMMENT ON TABLE settings_history IS 'Audit trail of runtime setting changes';
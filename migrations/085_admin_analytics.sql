@@ -0,0 +1,50 @@
+-- Admin Analytics
+-- Cohort funnels (registration -> verification -> first listing -> first transaction)
+-- need to know when an account was verified, not just whether it is. verified_at is
+-- maintained by trigger so every path that flips is_verified (admin verification,
+-- OAuth sign-up, scripts) records it. Existing verified accounts are backfilled from
+-- the latest admin verification in the audit log, falling back to updated_at.
+
+ALTER TABLE users ADD COLUMN IF NOT EXISTS verified_at TIMESTAMPTZ;
+
+UPDATE users u
+SET verified_at = COALESCE(
+    (
+        SELECT MAX(a.created_at)
+        FROM audit_logs a
+        WHERE a.event_type = 'admin_verify_user'
+          AND a.action = 'verify_user'
+          AND a.resource_id = u.id::text
+    ),
+    u.updated_at,
+    u.created_at
+)
+WHERE u.is_verified = TRUE AND u.verified_at IS NULL;
+
+CREATE OR REPLACE FUNCTION track_user_verified_at()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.is_verified IS TRUE THEN
+        IF TG_OP = 'INSERT' OR OLD.is_verified IS DISTINCT FROM TRUE THEN
+            NEW.verified_at := COALESCE(NEW.verified_at, NOW());
+        END IF;
+    ELSE
+        NEW.verified_at := NULL;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trigger_user_verified_at ON users;
+CREATE TRIGGER trigger_user_verified_at
+    BEFORE INSERT OR UPDATE OF is_verified ON users
+    FOR EACH ROW
+    EXECUTE FUNCTION track_user_verified_at();
+
+-- Time-bucketed analytics scans
+CREATE INDEX IF NOT EXISTS idx_users_created_at ON users(created_at);
+CREATE INDEX IF NOT EXISTS idx_inventory_user_created ON inventory(user_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_inquiries_created_at ON inquiries(created_at);
+CREATE INDEX IF NOT EXISTS idx_transactions_date_status ON transactions(transaction_date, status);
+
+COMMENT ON COLUMN users.verified_at IS 'When the account was (last) verified; NULL while unverified';
@@ -0,0 +1,62 @@
+-- Payload Captures for Compliance Investigations
+-- A superadmin opens a capture rule for one user, one route (template or path prefix)
+-- or both, for a bounded window and with the investigation's reason. While a rule is
+-- active, matching requests are stored with their request and response bodies, PII
+-- redacted. Captures expire after the rule's retention period and are purged by the
+-- payload capture scheduler; rules stop capturing at capture_until or when revoked.
+
+CREATE TABLE IF NOT EXISTS payload_capture_rules (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- At least one of user_id and route is set
+    user_id UUID REFERENCES users(id) ON DELETE CASCADE,
+    -- Route template (/api/inventory/:id) or path prefix (/api/marketplace/)
+    route TEXT,
+    -- NULL for every method
+    method TEXT,
+    -- Investigation or case reference; required
+    reason TEXT NOT NULL,
+    retention_days INTEGER NOT NULL DEFAULT 30,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    capture_until TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ,
+    revoked_by UUID REFERENCES users(id) ON DELETE SET NULL,
+
+    CONSTRAINT payload_capture_rules_target CHECK (user_id IS NOT NULL OR route IS NOT NULL),
+    CONSTRAINT payload_capture_rules_retention CHECK (retention_days BETWEEN 1 AND 90),
+    CONSTRAINT payload_capture_rules_window CHECK (capture_until > created_at)
+);
+
+CREATE INDEX IF NOT EXISTS idx_payload_capture_rules_active
+    ON payload_capture_rules(capture_until)
+    WHERE revoked_at IS NULL;
+
+CREATE TABLE IF NOT EXISTS payload_captures (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    rule_id UUID NOT NULL REFERENCES payload_capture_rules(id) ON DELETE CASCADE,
+    request_id UUID,
+    user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    method TEXT NOT NULL,
+    -- Route template when matched, else the path
+    route TEXT NOT NULL,
+    path TEXT NOT NULL,
+    query TEXT,
+    status SMALLINT NOT NULL,
+    latency_ms INTEGER NOT NULL,
+    request_content_type TEXT,
+    -- Redacted; NULL when empty, binary or too large to buffer
+    request_body TEXT,
+    response_content_type TEXT,
+    response_body TEXT,
+    -- A body was cut at the capture size limit
+    truncated BOOLEAN NOT NULL DEFAULT false,
+    captured_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_payload_captures_rule ON payload_captures(rule_id, captured_at DESC);
+CREATE INDEX IF NOT EXISTS idx_payload_captures_user ON payload_captures(user_id, captured_at DESC);
+CREATE INDEX IF NOT EXISTS idx_payload_captures_expires ON payload_captures(expires_at);
+
+COMMENT ON TABLE payload_capture_rules IS 'Admin-opened, time-bounded capture of request/response bodies for investigations';
+COMMENT ON TABLE payload_captures IS 'PII-redacted request/response bodies; deleted at expires_at';
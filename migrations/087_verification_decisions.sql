@@ -0,0 +1,89 @@
+-- Verification Decisions
+-- Admins approve or reject pending applicants from the verification queue, one at a
+-- time or in bulk, with a reason for every rejection. Rejected applicants leave the
+-- queue until they upload a license again. The daily verification_auto_reject job
+-- rejects applications without activity (sign-up or license upload) for the number
+-- of days in the `verification.auto_reject_days` runtime setting.
+
+ALTER TABLE users ADD COLUMN IF NOT EXISTS verification_rejected_at TIMESTAMPTZ;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS verification_rejection_reason TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_users_verification_pending ON users(created_at)
+    WHERE is_verified = false AND verification_rejected_at IS NULL;
+
+-- Every approval and rejection from the queue
+CREATE TABLE IF NOT EXISTS verification_decisions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    decision VARCHAR(20) NOT NULL CHECK (decision IN ('approved', 'rejected')),
+    reason TEXT,
+    -- NULL for automatic rejections
+    decided_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    automatic BOOLEAN NOT NULL DEFAULT false,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CHECK (decision = 'approved' OR reason IS NOT NULL)
+);
+
+CREATE INDEX IF NOT EXISTS idx_verification_decisions_user ON verification_decisions(user_id, created_at DESC);
+
+-- A new license upload puts a rejected applicant back in the queue
+CREATE OR REPLACE FUNCTION reopen_rejected_verification()
+RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE users
+    SET verification_rejected_at = NULL, verification_rejection_reason = NULL
+    WHERE id = NEW.user_id
+      AND is_verified = false
+      AND verification_rejected_at IS NOT NULL;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS reopen_rejected_verification ON business_licenses;
+CREATE TRIGGER reopen_rejected_verification
+    AFTER INSERT ON business_licenses
+    FOR EACH ROW
+    EXECUTE FUNCTION reopen_rejected_verification();
+
+ALTER TABLE alert_processing_log DROP CONSTRAINT IF EXISTS alert_processing_log_run_type_check;
+ALTER TABLE alert_processing_log ADD CONSTRAINT alert_processing_log_run_type_check
+CHECK (run_type IN (
+    'expiry_check',
+    'low_stock_check',
+    'watchlist_check',
+    'watchlist_trigger_check',
+    'scheduled_run',
+    'digest_delivery',
+    'announcement_delivery',
+    'notification_retention',
+    'data_purge',
+    'license_expiry_check',
+    'sanctions_list_sync',
+    'audit_chain_anchor',
+    'verification_auto_reject'
+));
+
+ALTER TABLE alert_scheduler_jobs DROP CONSTRAINT IF EXISTS alert_scheduler_jobs_job_type_check;
+ALTER TABLE alert_scheduler_jobs ADD CONSTRAINT alert_scheduler_jobs_job_type_check
+CHECK (job_type IN (
+    'expiry_check',
+    'low_stock_check',
+    'watchlist_check',
+    'watchlist_trigger_check',
+    'digest_delivery',
+    'announcement_delivery',
+    'notification_retention',
+    'data_purge',
+    'license_expiry_check',
+    'sanctions_list_sync',
+    'audit_chain_anchor',
+    'verification_auto_reject'
+));
+
+INSERT INTO alert_scheduler_jobs (job_type, interval_seconds, lease_seconds)
+VALUES ('verification_auto_reject', 86400, 1800)
+ON CONFLICT (job_type) DO NOTHING;
+
+COMMENT ON COLUMN users.verification_rejected_at IS 'Verification application rejected; out of the queue until a license is uploaded';
+COMMENT ON TABLE verification_decisions IS 'Approvals and rejections of verification applications, by admins or automatic';
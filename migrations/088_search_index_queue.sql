@@ -0,0 +1,221 @@
+-- Search Engine Index Queue
+-- With SEARCH_ENGINE configured, marketplace listings and the OpenFDA and EMA catalogs
+-- are mirrored into an external search engine (typo tolerance, facets). Changes reach
+-- it through search_index_queue: triggers enqueue the key of every changed record and
+-- the search index worker drains the queue, loading the current rows and upserting or
+-- deleting their documents. Postgres stays the source of truth and the search fallback.
+-- Triggers only enqueue for enabled indexes; the worker enables them on startup (queueing
+-- a full reindex) and disables them on instances without a search engine.
+
+CREATE TABLE IF NOT EXISTS search_indexes (
+    name VARCHAR(20) PRIMARY KEY CHECK (name IN ('marketplace', 'openfda', 'ema')),
+    enabled BOOLEAN NOT NULL DEFAULT false,
+    -- Set when the initial build has drained from the queue; searches use Postgres until then
+    ready_at TIMESTAMPTZ,
+    reindex_requested_at TIMESTAMPTZ,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+INSERT INTO search_indexes (name) VALUES ('marketplace'), ('openfda'), ('ema')
+ON CONFLICT (name) DO NOTHING;
+
+-- Keys may repeat; the worker indexes each key's current row once per batch
+CREATE TABLE IF NOT EXISTS search_index_queue (
+    id BIGSERIAL PRIMARY KEY,
+    index_name VARCHAR(20) NOT NULL REFERENCES search_indexes(name),
+    record_key TEXT NOT NULL,
+    queued_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_search_index_queue_index ON search_index_queue(index_name, id);
+
+CREATE OR REPLACE FUNCTION search_index_enabled(p_index VARCHAR)
+RETURNS BOOLEAN AS $$
+    SELECT COALESCE((SELECT enabled FROM search_indexes WHERE name = p_index), false);
+$$ LANGUAGE sql STABLE;
+
+-- ============================================================================
+-- Marketplace: listings, their products and their sellers
+-- ============================================================================
+
+CREATE OR REPLACE FUNCTION enqueue_inventory_search_index()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF search_index_enabled('marketplace') THEN
+        INSERT INTO search_index_queue (index_name, record_key)
+        VALUES ('marketplace', CASE WHEN TG_OP = 'DELETE' THEN OLD.id ELSE NEW.id END::text);
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trigger_inventory_search_index ON inventory;
+CREATE TRIGGER trigger_inventory_search_index
+    AFTER INSERT OR UPDATE OR DELETE ON inventory
+    FOR EACH ROW
+    EXECUTE FUNCTION enqueue_inventory_search_index();
+
+-- Product and seller changes re-index every listing of the product or seller
+CREATE OR REPLACE FUNCTION enqueue_pharmaceutical_search_index()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF search_index_enabled('marketplace') THEN
+        INSERT INTO search_index_queue (index_name, record_key)
+        SELECT 'marketplace', id::text FROM inventory WHERE pharmaceutical_id = NEW.id;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trigger_pharmaceutical_search_index ON pharmaceuticals;
+CREATE TRIGGER trigger_pharmaceutical_search_index
+    AFTER UPDATE ON pharmaceuticals
+    FOR EACH ROW
+    WHEN ((OLD.brand_name, OLD.generic_name, OLD.ndc_code, OLD.manufacturer, OLD.category,
+           OLD.description, OLD.strength, OLD.dosage_form, OLD.product_domain)
+          IS DISTINCT FROM
+          (NEW.brand_name, NEW.generic_name, NEW.ndc_code, NEW.manufacturer, NEW.category,
+           NEW.description, NEW.strength, NEW.dosage_form, NEW.product_domain))
+    EXECUTE FUNCTION enqueue_pharmaceutical_search_index();
+
+CREATE OR REPLACE FUNCTION enqueue_seller_search_index()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF search_index_enabled('marketplace') THEN
+        INSERT INTO search_index_queue (index_name, record_key)
+        SELECT 'marketplace', id::text FROM inventory WHERE user_id = NEW.id;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trigger_seller_search_index ON users;
+CREATE TRIGGER trigger_seller_search_index
+    AFTER UPDATE ON users
+    FOR EACH ROW
+    WHEN ((OLD.company_name, OLD.is_verified, OLD.country_code, OLD.listing_suspended_at, OLD.suspended_at)
+          IS DISTINCT FROM
+          (NEW.company_name, NEW.is_verified, NEW.country_code, NEW.listing_suspended_at, NEW.suspended_at))
+    EXECUTE FUNCTION enqueue_seller_search_index();
+
+-- ============================================================================
+-- Catalogs: statement-level, so a sync chunk enqueues with one INSERT. Updates
+-- only enqueue entries whose searchable fields changed; re-syncing an unchanged
+-- catalog enqueues nothing.
+-- ============================================================================
+
+CREATE OR REPLACE FUNCTION enqueue_openfda_search_index()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NOT search_index_enabled('openfda') THEN
+        RETURN NULL;
+    END IF;
+
+    IF TG_OP = 'INSERT' THEN
+        INSERT INTO search_index_queue (index_name, record_key)
+        SELECT 'openfda', product_ndc FROM new_rows;
+    ELSIF TG_OP = 'UPDATE' THEN
+        INSERT INTO search_index_queue (index_name, record_key)
+        SELECT 'openfda', n.product_ndc
+        FROM new_rows n
+        JOIN old_rows o ON o.id = n.id
+        WHERE (o.product_ndc, o.brand_name, o.generic_name, o.labeler_name, o.dosage_form, o.route,
+               o.strength, o.active_ingredients, o.product_type, o.marketing_category, o.pharm_class, o.dea_schedule)
+              IS DISTINCT FROM
+              (n.product_ndc, n.brand_name, n.generic_name, n.labeler_name, n.dosage_form, n.route,
+               n.strength, n.active_ingredients, n.product_type, n.marketing_category, n.pharm_class, n.dea_schedule);
+
+        -- A changed NDC removes the document under the old one
+        INSERT INTO search_index_queue (index_name, record_key)
+        SELECT 'openfda', o.product_ndc
+        FROM old_rows o
+        JOIN new_rows n ON n.id = o.id
+        WHERE o.product_ndc <> n.product_ndc;
+    ELSE
+        INSERT INTO search_index_queue (index_name, record_key)
+        SELECT 'openfda', product_ndc FROM old_rows;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trigger_openfda_search_index_insert ON openfda_catalog;
+CREATE TRIGGER trigger_openfda_search_index_insert
+    AFTER INSERT ON openfda_catalog
+    REFERENCING NEW TABLE AS new_rows
+    FOR EACH STATEMENT
+    EXECUTE FUNCTION enqueue_openfda_search_index();
+
+DROP TRIGGER IF EXISTS trigger_openfda_search_index_update ON openfda_catalog;
+CREATE TRIGGER trigger_openfda_search_index_update
+    AFTER UPDATE ON openfda_catalog
+    REFERENCING OLD TABLE AS old_rows NEW TABLE AS new_rows
+    FOR EACH STATEMENT
+    EXECUTE FUNCTION enqueue_openfda_search_index();
+
+DROP TRIGGER IF EXISTS trigger_openfda_search_index_delete ON openfda_catalog;
+CREATE TRIGGER trigger_openfda_search_index_delete
+    AFTER DELETE ON openfda_catalog
+    REFERENCING OLD TABLE AS old_rows
+    FOR EACH STATEMENT
+    EXECUTE FUNCTION enqueue_openfda_search_index();
+
+CREATE OR REPLACE FUNCTION enqueue_ema_search_index()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NOT search_index_enabled('ema') THEN
+        RETURN NULL;
+    END IF;
+
+    IF TG_OP = 'INSERT' THEN
+        INSERT INTO search_index_queue (index_name, record_key)
+        SELECT 'ema', eu_number FROM new_rows;
+    ELSIF TG_OP = 'UPDATE' THEN
+        INSERT INTO search_index_queue (index_name, record_key)
+        SELECT 'ema', n.eu_number
+        FROM new_rows n
+        JOIN old_rows o ON o.id = n.id
+        WHERE (o.eu_number, o.product_name, o.inn_name, o.therapeutic_indication, o.mah_name,
+               o.authorization_status, o.pharmaceutical_form, o.atc_code, o.therapeutic_area,
+               o.orphan_designation, o.language_code, o.active_substances)
+              IS DISTINCT FROM
+              (n.eu_number, n.product_name, n.inn_name, n.therapeutic_indication, n.mah_name,
+               n.authorization_status, n.pharmaceutical_form, n.atc_code, n.therapeutic_area,
+               n.orphan_designation, n.language_code, n.active_substances);
+
+        INSERT INTO search_index_queue (index_name, record_key)
+        SELECT 'ema', o.eu_number
+        FROM old_rows o
+        JOIN new_rows n ON n.id = o.id
+        WHERE o.eu_number <> n.eu_number;
+    ELSE
+        INSERT INTO search_index_queue (index_name, record_key)
+        SELECT 'ema', eu_number FROM old_rows;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trigger_ema_search_index_insert ON ema_catalog;
+CREATE TRIGGER trigger_ema_search_index_insert
+    AFTER INSERT ON ema_catalog
+    REFERENCING NEW TABLE AS new_rows
+    FOR EACH STATEMENT
+    EXECUTE FUNCTION enqueue_ema_search_index();
+
+DROP TRIGGER IF EXISTS trigger_ema_search_index_update ON ema_catalog;
+CREATE TRIGGER trigger_ema_search_index_update
+    AFTER UPDATE ON ema_catalog
+    REFERENCING OLD TABLE AS old_rows NEW TABLE AS new_rows
+    FOR EACH STATEMENT
+    EXECUTE FUNCTION enqueue_ema_search_index();
+
+DROP TRIGGER IF EXISTS trigger_ema_search_index_delete ON ema_catalog;
+CREATE TRIGGER trigger_ema_search_index_delete
+    AFTER DELETE ON ema_catalog
+    REFERENCING OLD TABLE AS old_rows
+    FOR EACH STATEMENT
+    EXECUTE FUNCTION enqueue_ema_search_index();
+
+COMMENT ON TABLE search_indexes IS 'Search engine indexes mirrored from Postgres; triggers only enqueue for enabled ones';
+COMMENT ON TABLE search_index_queue IS 'Keys of records changed since the search index worker last indexed them';
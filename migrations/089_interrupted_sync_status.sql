@@ -0,0 +1,21 @@
+-- Interrupted Sync Status
+-- On SIGTERM an instance drains its in-flight work before exiting. Syncs stopped by
+-- the shutdown (at their next batch, or when the drain timeout ends) are recorded as
+-- 'interrupted' instead of staying in_progress/running until they go stale.
+-- openfda_sync_log, ema_sync_log and catalog_sync_log have no status constraint.
+
+ALTER TABLE erp_sync_logs DROP CONSTRAINT IF EXISTS erp_sync_logs_status_check;
+ALTER TABLE erp_sync_logs ADD CONSTRAINT erp_sync_logs_status_check
+    CHECK (status IN ('running', 'success', 'failed', 'partial', 'interrupted'));
+
+ALTER TABLE erp_connections DROP CONSTRAINT IF EXISTS erp_connections_last_sync_status_check;
+ALTER TABLE erp_connections ADD CONSTRAINT erp_connections_last_sync_status_check
+    CHECK (last_sync_status IN ('success', 'failed', 'partial', 'running', 'interrupted'));
+
+ALTER TABLE sanctions_list_syncs DROP CONSTRAINT IF EXISTS sanctions_list_syncs_status_check;
+ALTER TABLE sanctions_list_syncs ADD CONSTRAINT sanctions_list_syncs_status_check
+    CHECK (status IN ('running', 'completed', 'failed', 'interrupted'));
+
+COMMENT ON COLUMN openfda_sync_log.status IS 'in_progress, completed, failed, suspect or interrupted (stopped by a shutdown; resumable from resume_state)';
+COMMENT ON COLUMN ema_sync_log.status IS 'in_progress, completed, failed, cancelled, suspect or interrupted (stopped by a shutdown)';
+COMMENT ON COLUMN catalog_sync_log.status IS 'in_progress, completed, failed, suspect or interrupted (stopped by a shutdown)';
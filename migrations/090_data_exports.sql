@@ -0,0 +1,59 @@
+-- Data Exports
+-- Large exports (inventory, transactions, audit logs, OpenFDA and EMA catalogs) are
+-- written to file storage by a background job instead of holding an HTTP response open
+-- for minutes. The client polls the export for progress and downloads the finished file
+-- through a signed link that expires after a few minutes; files are deleted once the
+-- export expires.
+
+ALTER TABLE background_job_types DROP CONSTRAINT IF EXISTS background_job_types_job_type_check;
+ALTER TABLE background_job_types ADD CONSTRAINT background_job_types_job_type_check
+    CHECK (job_type IN (
+        'erp_sync',
+        'ai_import',
+        'openfda_sync',
+        'document_batch',
+        'sanctions_list_sync',
+        'data_export'
+    ));
+
+INSERT INTO background_job_types (job_type, max_concurrency, max_attempts, lease_seconds) VALUES
+    ('data_export', 2, 3, 300)
+ON CONFLICT (job_type) DO NOTHING;
+
+CREATE TABLE IF NOT EXISTS data_exports (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    export_type VARCHAR(30) NOT NULL
+        CHECK (export_type IN ('inventory', 'transactions', 'audit_logs', 'openfda_catalog', 'ema_catalog')),
+    format VARCHAR(10) NOT NULL CHECK (format IN ('csv', 'ndjson')),
+    filters JSONB NOT NULL DEFAULT '{}'::jsonb,
+    status VARCHAR(20) NOT NULL DEFAULT 'queued'
+        CHECK (status IN ('queued', 'running', 'completed', 'failed', 'expired')),
+
+    -- Progress: rows matching the filters (counted when the job starts) and rows written
+    total_rows BIGINT,
+    rows_written BIGINT NOT NULL DEFAULT 0,
+
+    -- Finished file, relative to FILE_STORAGE_PATH
+    file_path TEXT,
+    file_size_bytes BIGINT,
+    content_hash VARCHAR(64),
+
+    error TEXT,
+    -- The file is deleted and the export marked expired after this time
+    expires_at TIMESTAMPTZ,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    started_at TIMESTAMPTZ,
+    completed_at TIMESTAMPTZ,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_data_exports_user
+    ON data_exports(user_id, created_at DESC);
+
+CREATE INDEX IF NOT EXISTS idx_data_exports_expiring
+    ON data_exports(expires_at)
+    WHERE status = 'completed';
+
+COMMENT ON TABLE data_exports IS 'Asynchronous exports written to file storage by background jobs, downloadable through expiring signed links';
@@ -0,0 +1,112 @@
+-- Schema Migration Ledger
+-- The server checks at startup that every file in migrations/ is recorded here and
+-- refuses to boot otherwise (DATABASE_MIGRATIONS=run applies pending files instead).
+-- Files are tracked by name: versions 007 and 008 each name two files, and databases
+-- migrated with psql have no record of what was applied. Applying this file records
+-- every migration up to and including it, as psql-migrated databases have run them.
+
+CREATE TABLE IF NOT EXISTS schema_migrations (
+    filename TEXT PRIMARY KEY,
+    -- SHA-256 of the file as applied; NULL for files recorded by this baseline
+    checksum VARCHAR(64),
+    execution_ms BIGINT,
+    applied_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+INSERT INTO schema_migrations (filename) VALUES
+    ('001_initial_schema.sql'),
+    ('002_openfda_catalog.sql'),
+    ('003_inquiry_messaging.sql'),
+    ('004_ai_import_system.sql'),
+    ('005_ai_assistant_features.sql'),
+    ('006_smart_alerts_system.sql'),
+    ('007_comprehensive_audit_system.sql'),
+    ('007_encrypt_pii_fields.sql'),
+    ('008_encrypted_only_columns.sql'),
+    ('008_production_mfa_totp.sql'),
+    ('009_regulatory_document_system.sql'),
+    ('010_erp_integration_system.sql'),
+    ('011_erp_ai_features.sql'),
+    ('012_admin_role_system.sql'),
+    ('013_add_webhook_security.sql'),
+    ('014_secure_mfa_trigger_bypass.sql'),
+    ('015_api_quota_system.sql'),
+    ('016_encryption_key_rotation.sql'),
+    ('017_ema_catalog.sql'),
+    ('018_openfda_sync_progress.sql'),
+    ('019_oauth_providers.sql'),
+    ('020_product_images.sql'),
+    ('021_catalog_change_subscriptions.sql'),
+    ('022_structured_ingredients.sql'),
+    ('023_product_domains_and_devices.sql'),
+    ('024_openfda_delta_checkpoints.sql'),
+    ('025_openfda_resumable_sync.sql'),
+    ('026_openfda_recalls.sql'),
+    ('027_ema_documents.sql'),
+    ('028_regulator_catalogs.sql'),
+    ('029_ema_sync_progress.sql'),
+    ('030_sync_anomaly_detection.sql'),
+    ('031_openfda_catalog_history.sql'),
+    ('032_sync_source_settings.sql'),
+    ('033_rxnorm_atc_mapping.sql'),
+    ('034_flat_file_erp.sql'),
+    ('035_edi_exchange.sql'),
+    ('036_erp_sync_scheduling.sql'),
+    ('037_erp_pending_mappings.sql'),
+    ('038_erp_mapping_item_cache.sql'),
+    ('039_connection_field_mapping_templates.sql'),
+    ('040_erp_conflict_review.sql'),
+    ('041_erp_sandbox_mode.sql'),
+    ('042_erp_order_exports.sql'),
+    ('043_erp_delta_sync.sql'),
+    ('044_erp_item_catalog.sql'),
+    ('045_erp_connection_entities.sql'),
+    ('046_accounting_connections.sql'),
+    ('047_semantic_search.sql'),
+    ('048_ai_document_extraction.sql'),
+    ('049_nl_query_reports.sql'),
+    ('050_ai_pricing_suggestions.sql'),
+    ('051_ai_response_cache.sql'),
+    ('052_ai_quota_tiers.sql'),
+    ('053_inquiry_assistant_languages.sql'),
+    ('054_ai_import_mapping_memory.sql'),
+    ('055_ai_import_row_review_and_rollback.sql'),
+    ('056_regulatory_document_citations.sql'),
+    ('057_knowledge_base_management.sql'),
+    ('058_anomaly_detection.sql'),
+    ('059_inquiry_assistant_memory.sql'),
+    ('060_outbound_webhooks.sql'),
+    ('061_alert_chat_channels.sql'),
+    ('062_realtime_notifications.sql'),
+    ('063_notification_digests.sql'),
+    ('064_watchlist_triggers.sql'),
+    ('065_alert_scheduler_jobs.sql'),
+    ('066_watchlist_check_frequency.sql'),
+    ('067_announcements.sql'),
+    ('068_notification_retention.sql'),
+    ('069_dscsa_transaction_data.sql'),
+    ('070_product_verification.sql'),
+    ('071_electronic_signatures.sql'),
+    ('072_data_retention.sql'),
+    ('073_legal_holds.sql'),
+    ('074_regulatory_document_versions.sql'),
+    ('075_temperature_excursions.sql'),
+    ('076_license_registry.sql'),
+    ('077_sanctions_screening.sql'),
+    ('078_regulatory_rules.sql'),
+    ('079_audit_log_chain.sql'),
+    ('080_regulatory_document_batches.sql'),
+    ('081_background_jobs.sql'),
+    ('082_usage_metering.sql'),
+    ('083_user_suspensions.sql'),
+    ('084_runtime_settings.sql'),
+    ('085_admin_analytics.sql'),
+    ('086_payload_captures.sql'),
+    ('087_verification_decisions.sql'),
+    ('088_search_index_queue.sql'),
+    ('089_interrupted_sync_status.sql'),
+    ('090_data_exports.sql'),
+    ('091_schema_migrations.sql')
+ON CONFLICT (filename) DO NOTHING;
+
+COMMENT ON TABLE schema_migrations IS 'Migration files applied to this database; checked by the startup preflight';
@@ -0,0 +1,239 @@
+-- Summary Aggregates
+-- Admin stats, the manufacturer/category lists and marketplace facet counts scanned
+-- their source tables on every request. They now read summaries:
+-- - summary_counts: row counts kept current by statement-level triggers (one counter
+--   update per statement, from its transition tables). refresh_summary_counts()
+--   recounts them to correct drift (TRUNCATE, triggers disabled during a restore).
+-- - materialized views for the lists and facets. Any write to a source table marks the
+--   views built from it stale in summary_refreshes; the summary refresh job rebuilds
+--   stale views (REFRESH ... CONCURRENTLY, so reads never wait) and records when.
+
+CREATE TABLE IF NOT EXISTS summary_counts (
+    name VARCHAR(50) PRIMARY KEY,
+    value BIGINT NOT NULL DEFAULT 0,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE OR REPLACE FUNCTION add_summary_count(counter TEXT, delta BIGINT)
+RETURNS VOID AS $$
+BEGIN
+    IF delta <> 0 THEN
+        UPDATE summary_counts SET value = value + delta, updated_at = NOW() WHERE name = counter;
+    END IF;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Matches the admin stats definitions: pending verification is an unverified 'user'
+-- account, admins include superadmins
+CREATE OR REPLACE FUNCTION count_users_summary()
+RETURNS TRIGGER AS $$
+DECLARE
+    total BIGINT := 0;
+    verified BIGINT := 0;
+    pending BIGINT := 0;
+    admins BIGINT := 0;
+BEGIN
+    IF TG_OP IN ('INSERT', 'UPDATE') THEN
+        SELECT COUNT(*),
+               COUNT(*) FILTER (WHERE is_verified = TRUE),
+               COUNT(*) FILTER (WHERE role = 'user' AND is_verified = FALSE),
+               COUNT(*) FILTER (WHERE role IN ('admin', 'superadmin'))
+        INTO total, verified, pending, admins
+        FROM new_rows;
+    END IF;
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        SELECT total - COUNT(*),
+               verified - COUNT(*) FILTER (WHERE is_verified = TRUE),
+               pending - COUNT(*) FILTER (WHERE role = 'user' AND is_verified = FALSE),
+               admins - COUNT(*) FILTER (WHERE role IN ('admin', 'superadmin'))
+        INTO total, verified, pending, admins
+        FROM old_rows;
+    END IF;
+
+    PERFORM add_summary_count('users', total);
+    PERFORM add_summary_count('users_verified', verified);
+    PERFORM add_summary_count('users_pending_verification', pending);
+    PERFORM add_summary_count('admins', admins);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Row count of the table into the counter named by the trigger argument
+CREATE OR REPLACE FUNCTION count_rows_summary()
+RETURNS TRIGGER AS $$
+DECLARE
+    delta BIGINT;
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        SELECT COUNT(*) INTO delta FROM new_rows;
+    ELSE
+        SELECT -COUNT(*) INTO delta FROM old_rows;
+    END IF;
+    PERFORM add_summary_count(TG_ARGV[0], delta);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Transition tables need one trigger per event
+DROP TRIGGER IF EXISTS trigger_summary_users_insert ON users;
+CREATE TRIGGER trigger_summary_users_insert
+    AFTER INSERT ON users REFERENCING NEW TABLE AS new_rows
+    FOR EACH STATEMENT EXECUTE FUNCTION count_users_summary();
+DROP TRIGGER IF EXISTS trigger_summary_users_update ON users;
+CREATE TRIGGER trigger_summary_users_update
+    AFTER UPDATE ON users REFERENCING OLD TABLE AS old_rows NEW TABLE AS new_rows
+    FOR EACH STATEMENT EXECUTE FUNCTION count_users_summary();
+DROP TRIGGER IF EXISTS trigger_summary_users_delete ON users;
+CREATE TRIGGER trigger_summary_users_delete
+    AFTER DELETE ON users REFERENCING OLD TABLE AS old_rows
+    FOR EACH STATEMENT EXECUTE FUNCTION count_users_summary();
+
+DROP TRIGGER IF EXISTS trigger_summary_inventory_insert ON inventory;
+CREATE TRIGGER trigger_summary_inventory_insert
+    AFTER INSERT ON inventory REFERENCING NEW TABLE AS new_rows
+    FOR EACH STATEMENT EXECUTE FUNCTION count_rows_summary('inventory_items');
+DROP TRIGGER IF EXISTS trigger_summary_inventory_delete ON inventory;
+CREATE TRIGGER trigger_summary_inventory_delete
+    AFTER DELETE ON inventory REFERENCING OLD TABLE AS old_rows
+    FOR EACH STATEMENT EXECUTE FUNCTION count_rows_summary('inventory_items');
+
+DROP TRIGGER IF EXISTS trigger_summary_transactions_insert ON transactions;
+CREATE TRIGGER trigger_summary_transactions_insert
+    AFTER INSERT ON transactions REFERENCING NEW TABLE AS new_rows
+    FOR EACH STATEMENT EXECUTE FUNCTION count_rows_summary('transactions');
+DROP TRIGGER IF EXISTS trigger_summary_transactions_delete ON transactions;
+CREATE TRIGGER trigger_summary_transactions_delete
+    AFTER DELETE ON transactions REFERENCING OLD TABLE AS old_rows
+    FOR EACH STATEMENT EXECUTE FUNCTION count_rows_summary('transactions');
+
+-- Recount every counter. The counters are locked first: writers whose trigger already
+-- ran have committed by then (and are counted), later ones wait and add to the recount.
+CREATE OR REPLACE FUNCTION refresh_summary_counts()
+RETURNS INTEGER AS $$
+DECLARE
+    corrected INTEGER;
+BEGIN
+    PERFORM 1 FROM summary_counts ORDER BY name FOR UPDATE;
+
+    WITH counts(name, value) AS (
+        SELECT 'users', COUNT(*) FROM users
+        UNION ALL SELECT 'users_verified', COUNT(*) FROM users WHERE is_verified = TRUE
+        UNION ALL SELECT 'users_pending_verification', COUNT(*) FROM users WHERE role = 'user' AND is_verified = FALSE
+        UNION ALL SELECT 'admins', COUNT(*) FROM users WHERE role IN ('admin', 'superadmin')
+        UNION ALL SELECT 'inventory_items', COUNT(*) FROM inventory
+        UNION ALL SELECT 'transactions', COUNT(*) FROM transactions
+    ), upserted AS (
+        INSERT INTO summary_counts (name, value)
+        SELECT name, value FROM counts
+        ON CONFLICT (name) DO UPDATE
+        SET value = EXCLUDED.value, updated_at = NOW()
+        WHERE summary_counts.value <> EXCLUDED.value
+        RETURNING 1
+    )
+    SELECT COUNT(*) INTO corrected FROM upserted;
+
+    RETURN corrected;
+END;
+$$ LANGUAGE plpgsql;
+
+SELECT refresh_summary_counts();
+
+-- ============================================================================
+-- MATERIALIZED VIEWS
+-- ============================================================================
+
+CREATE TABLE IF NOT EXISTS summary_refreshes (
+    view_name VARCHAR(60) PRIMARY KEY,
+    refreshed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- First write to a source table since the last refresh; NULL while fresh
+    stale_since TIMESTAMPTZ,
+    duration_ms BIGINT
+);
+
+-- Mark the views named by the trigger arguments stale. Once stale the UPDATE matches
+-- nothing, so busy tables don't contend on these rows.
+CREATE OR REPLACE FUNCTION mark_summaries_stale()
+RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE summary_refreshes
+    SET stale_since = NOW()
+    WHERE view_name = ANY(TG_ARGV) AND stale_since IS NULL;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE MATERIALIZED VIEW IF NOT EXISTS pharma_manufacturer_counts AS
+SELECT manufacturer, COUNT(*) AS product_count
+FROM pharmaceuticals
+GROUP BY manufacturer;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_pharma_manufacturer_counts ON pharma_manufacturer_counts(manufacturer);
+
+CREATE MATERIALIZED VIEW IF NOT EXISTS pharma_category_counts AS
+SELECT category, COUNT(*) AS product_count
+FROM pharmaceuticals
+WHERE category IS NOT NULL
+GROUP BY category;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_pharma_category_counts ON pharma_category_counts(category);
+
+CREATE MATERIALIZED VIEW IF NOT EXISTS openfda_manufacturer_counts AS
+SELECT labeler_name AS manufacturer, COUNT(*) AS product_count
+FROM openfda_catalog
+WHERE labeler_name IS NOT NULL AND labeler_name <> ''
+GROUP BY labeler_name;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_openfda_manufacturer_counts ON openfda_manufacturer_counts(manufacturer);
+CREATE INDEX IF NOT EXISTS idx_openfda_manufacturer_counts_top
+    ON openfda_manufacturer_counts(product_count DESC, manufacturer);
+
+-- Facet values of the listings on the marketplace, as the search engine indexes them
+CREATE MATERIALIZED VIEW IF NOT EXISTS marketplace_facet_counts AS
+SELECT f.facet, f.value, COUNT(*) AS listing_count
+FROM inventory i
+JOIN pharmaceuticals p ON i.pharmaceutical_id = p.id
+JOIN users u ON i.user_id = u.id
+CROSS JOIN LATERAL (VALUES
+    ('manufacturer', p.manufacturer::text),
+    ('category', p.category::text),
+    ('dosage_form', p.dosage_form::text),
+    ('product_domain', p.product_domain::text),
+    ('seller_verified', u.is_verified::text),
+    ('seller_country', u.country_code::text)
+) AS f(facet, value)
+WHERE i.status = 'available' AND i.taken_down_at IS NULL
+  AND u.listing_suspended_at IS NULL AND u.suspended_at IS NULL
+  AND f.value IS NOT NULL
+GROUP BY f.facet, f.value;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_marketplace_facet_counts ON marketplace_facet_counts(facet, value);
+
+INSERT INTO summary_refreshes (view_name) VALUES
+    ('pharma_manufacturer_counts'),
+    ('pharma_category_counts'),
+    ('openfda_manufacturer_counts'),
+    ('marketplace_facet_counts')
+ON CONFLICT (view_name) DO NOTHING;
+
+DROP TRIGGER IF EXISTS trigger_summary_stale_pharmaceuticals ON pharmaceuticals;
+CREATE TRIGGER trigger_summary_stale_pharmaceuticals
+    AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON pharmaceuticals
+    FOR EACH STATEMENT
+    EXECUTE FUNCTION mark_summaries_stale('pharma_manufacturer_counts', 'pharma_category_counts', 'marketplace_facet_counts');
+
+DROP TRIGGER IF EXISTS trigger_summary_stale_openfda ON openfda_catalog;
+CREATE TRIGGER trigger_summary_stale_openfda
+    AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON openfda_catalog
+    FOR EACH STATEMENT
+    EXECUTE FUNCTION mark_summaries_stale('openfda_manufacturer_counts');
+
+DROP TRIGGER IF EXISTS trigger_summary_stale_inventory ON inventory;
+CREATE TRIGGER trigger_summary_stale_inventory
+    AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON inventory
+    FOR EACH STATEMENT
+    EXECUTE FUNCTION mark_summaries_stale('marketplace_facet_counts');
+
+DROP TRIGGER IF EXISTS trigger_summary_stale_users ON users;
+CREATE TRIGGER trigger_summary_stale_users
+    AFTER UPDATE OF is_verified, country_code, listing_suspended_at, suspended_at OR DELETE ON users
+    FOR EACH STATEMENT
+    EXECUTE FUNCTION mark_summaries_stale('marketplace_facet_counts');
+
+COMMENT ON TABLE summary_counts IS 'Row counts for admin stats, kept current by statement-level triggers';
+COMMENT ON TABLE summary_refreshes IS 'Last refresh and staleness of each summary materialized view';
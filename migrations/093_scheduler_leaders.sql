@@ -0,0 +1,15 @@
+-- Scheduler Leaders
+-- Schedulers that must run once across all replicas only run on the instance leading
+-- them. Leadership is a session advisory lock per scheduler (see SchedulerLeader); this
+-- table records which instance and backend hold each lock, with a heartbeat, so a
+-- contender can tell a leader that stopped heartbeating from a busy one and the ops
+-- dashboard can show who runs what.
+
+CREATE TABLE IF NOT EXISTS scheduler_leaders (
+    scheduler VARCHAR(100) PRIMARY KEY,
+    instance_id VARCHAR(255) NOT NULL,
+    -- pg_backend_pid() of the session holding the lock
+    backend_pid INTEGER NOT NULL,
+    acquired_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    heartbeat_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
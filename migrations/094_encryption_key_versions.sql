@@ -0,0 +1,33 @@
+-- Encryption Key Versions
+-- Encrypted text carries the version of the data encryption key (DEK) it was written
+-- with ("v3:..."; untagged ciphertext is the ENCRYPTION_KEY itself, version 0), so a
+-- rotation no longer strands existing data on the old key: the encryption_reencrypt
+-- background job moves it to the active key, and the admin encryption dashboard shows
+-- the share of rows per table already on it.
+--
+-- Every instance reloads its keyring periodically. A new key only becomes the one new
+-- ciphertext is written with at activates_at, once every instance has loaded it and
+-- can read what it writes.
+
+ALTER TABLE data_encryption_keys ADD COLUMN IF NOT EXISTS activates_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+
+-- Instances of the previous release can't read tagged ciphertext: give the rolling
+-- deploy time to replace them before the existing key is used
+UPDATE data_encryption_keys SET activates_at = NOW() + INTERVAL '10 minutes' WHERE status <> 'rotated';
+
+ALTER TABLE background_job_types DROP CONSTRAINT IF EXISTS background_job_types_job_type_check;
+ALTER TABLE background_job_types ADD CONSTRAINT background_job_types_job_type_check
+    CHECK (job_type IN (
+        'erp_sync',
+        'ai_import',
+        'openfda_sync',
+        'document_batch',
+        'sanctions_list_sync',
+        'data_export',
+        'encryption_reencrypt'
+    ));
+
+-- One at a time: the job walks every encrypted table itself
+INSERT INTO background_job_types (job_type, max_concurrency, max_attempts, lease_seconds) VALUES
+    ('encryption_reencrypt', 1, 5, 120)
+ON CONFLICT (job_type) DO NOTHING;
@@ -0,0 +1,18 @@
+-- FHIR Clients
+-- Hospital systems read the FHIR facade (/fhir) as a registered client: they exchange
+-- the client id and secret at /fhir/auth/token (SMART backend services style,
+-- client_credentials grant) for a short-lived JWT of the account that registered them.
+-- Only a SHA-256 of the secret is stored; secrets are random, so a fast hash is enough.
+
+CREATE TABLE IF NOT EXISTS fhir_clients (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name VARCHAR(255) NOT NULL,
+    client_id VARCHAR(64) NOT NULL UNIQUE,
+    secret_hash VARCHAR(64) NOT NULL,
+    last_used_at TIMESTAMPTZ,
+    revoked_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_fhir_clients_user ON fhir_clients(user_id);
@@ -0,0 +1,64 @@
+-- Batch Operations
+-- POST /api/batch takes up to 100 operations of one kind (create inquiries, update
+-- inventory, dismiss notifications) and runs them in a batch_operation background job.
+-- Each operation is an item that commits or fails on its own, so one bad item doesn't
+-- undo the others; the client polls the batch and reads the per-item results.
+
+ALTER TABLE background_job_types DROP CONSTRAINT IF EXISTS background_job_types_job_type_check;
+ALTER TABLE background_job_types ADD CONSTRAINT background_job_types_job_type_check
+    CHECK (job_type IN (
+        'erp_sync',
+        'ai_import',
+        'openfda_sync',
+        'document_batch',
+        'sanctions_list_sync',
+        'data_export',
+        'encryption_reencrypt',
+        'batch_operation'
+    ));
+
+INSERT INTO background_job_types (job_type, max_concurrency, max_attempts, lease_seconds) VALUES
+    ('batch_operation', 4, 3, 60)
+ON CONFLICT (job_type) DO NOTHING;
+
+CREATE TABLE IF NOT EXISTS batch_operations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    operation VARCHAR(30) NOT NULL
+        CHECK (operation IN ('create_inquiries', 'update_inventory', 'dismiss_notifications')),
+    status VARCHAR(30) NOT NULL DEFAULT 'queued'
+        CHECK (status IN ('queued', 'running', 'completed', 'completed_with_errors', 'failed')),
+    total_items INTEGER NOT NULL,
+    processed_items INTEGER NOT NULL DEFAULT 0,
+    succeeded_items INTEGER NOT NULL DEFAULT 0,
+    failed_items INTEGER NOT NULL DEFAULT 0,
+    error TEXT,
+    -- Background job running the batch
+    job_id UUID REFERENCES background_jobs(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    started_at TIMESTAMPTZ,
+    completed_at TIMESTAMPTZ,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_batch_operations_user
+    ON batch_operations(user_id, created_at DESC);
+
+CREATE TABLE IF NOT EXISTS batch_operation_items (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    batch_id UUID NOT NULL REFERENCES batch_operations(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    -- The operation as submitted
+    input JSONB NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'succeeded', 'failed')),
+    -- What the operation returned (the inquiry or inventory item; the dismissed id)
+    result JSONB,
+    error TEXT,
+    -- Problem code of the error (see problem_details), e.g. 'not_found' or 'conflict'
+    error_code VARCHAR(50),
+    processed_at TIMESTAMPTZ,
+    UNIQUE (batch_id, position)
+);
+
+COMMENT ON TABLE batch_operations IS 'Homogeneous operations submitted together and run by a background job';
+COMMENT ON TABLE batch_operation_items IS 'One operation of a batch with its own result';
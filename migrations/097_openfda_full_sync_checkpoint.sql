@@ -0,0 +1,7 @@
+-- OpenFDA Full Sync Age
+-- Delta checkpoints record the date of the last full sync (full_synced_on) next to the
+-- date of the run that wrote them. Delta runs carry it forward, so the delta_max_age_days
+-- limit counts from the last full sync and a full resync still runs periodically.
+-- Checkpoints without it force one full sync.
+
+COMMENT ON COLUMN openfda_sync_log.checkpoint IS 'Delta checkpoint: {dataset_last_updated, partition_totals, synced_on, full_synced_on}';
@@ -0,0 +1,16 @@
+-- Electronic Signature Re-authentication Log
+-- Every password check at signing (21 CFR Part 11) is logged, like MFA verifications in
+-- mfa_verification_log; a signer with too many failed passwords in the window is locked
+-- out of signing until the window has passed.
+
+CREATE TABLE IF NOT EXISTS signature_reauth_log (
+    id BIGSERIAL PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    document_id UUID REFERENCES regulatory_documents(id) ON DELETE SET NULL,
+    result VARCHAR(50) NOT NULL, -- success, invalid_password, locked_out
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_signature_reauth_log_user ON signature_reauth_log(user_id, created_at DESC);
+CREATE INDEX idx_signature_reauth_log_failures ON signature_reauth_log(user_id, created_at DESC)
+    WHERE result = 'invalid_password';
@@ -0,0 +1,29 @@
+-- Catalog Sync Jobs
+-- OpenFDA device and recall syncs, EMA syncs and regulator catalog syncs run as
+-- background jobs like the OpenFDA drug catalog sync, so a sync whose instance restarts
+-- is picked up again by another worker instead of being lost.
+
+ALTER TABLE background_job_types DROP CONSTRAINT IF EXISTS background_job_types_job_type_check;
+ALTER TABLE background_job_types ADD CONSTRAINT background_job_types_job_type_check
+    CHECK (job_type IN (
+        'erp_sync',
+        'ai_import',
+        'openfda_sync',
+        'document_batch',
+        'sanctions_list_sync',
+        'data_export',
+        'encryption_reencrypt',
+        'batch_operation',
+        'openfda_device_sync',
+        'openfda_recall_sync',
+        'ema_sync',
+        'regulator_catalog_sync'
+    ));
+
+-- Regulator catalog syncs run one per source, so up to one per connector at once
+INSERT INTO background_job_types (job_type, max_concurrency, max_attempts, lease_seconds) VALUES
+    ('openfda_device_sync', 1, 3, 600),
+    ('openfda_recall_sync', 1, 3, 600),
+    ('ema_sync', 1, 3, 600),
+    ('regulator_catalog_sync', 3, 3, 600)
+ON CONFLICT (job_type) DO NOTHING;
@@ -0,0 +1,23 @@
+-- Session Revocation Broadcast
+-- Announce every change to users.sessions_revoked_at on the atlas_session_revocations
+-- channel, so each API instance adds the cutoff to its token blacklist as soon as the
+-- revoking transaction commits instead of on its next periodic sync.
+
+CREATE OR REPLACE FUNCTION notify_session_revocation()
+RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM pg_notify('atlas_session_revocations', json_build_object(
+        'user_id', NEW.id,
+        'revoked_at', FLOOR(EXTRACT(EPOCH FROM NEW.sessions_revoked_at))::BIGINT
+    )::text);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trigger_session_revocation ON users;
+CREATE TRIGGER trigger_session_revocation
+    AFTER UPDATE OF sessions_revoked_at ON users
+    FOR EACH ROW
+    WHEN (NEW.sessions_revoked_at IS NOT NULL
+          AND NEW.sessions_revoked_at IS DISTINCT FROM OLD.sessions_revoked_at)
+    EXECUTE FUNCTION notify_session_revocation();
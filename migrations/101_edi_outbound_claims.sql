@@ -0,0 +1,12 @@
+-- EDI Outbound Claims
+-- An 850 send first records a 'pending' document, and only one pending or sent 850 may
+-- exist per transaction, so concurrent sends of the same order deliver it once without
+-- holding a lock while the partner's AS2/SFTP endpoint is contacted.
+
+ALTER TABLE edi_documents DROP CONSTRAINT IF EXISTS edi_documents_status_check;
+ALTER TABLE edi_documents ADD CONSTRAINT edi_documents_status_check
+    CHECK (status IN ('pending', 'sent', 'failed', 'applied', 'unmatched', 'rejected'));
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_edi_documents_outbound_850_claim
+ON edi_documents(transaction_id)
+WHERE direction = 'outbound' AND transaction_set = '850' AND status IN ('pending', 'sent');
@@ -1,9 +1,18 @@
 pub mod tls;
 pub mod oauth;
+pub mod replica;
+pub mod pool;
+pub mod preflight;
 
 use std::env;
+use std::sync::Arc;
 use anyhow::Result;
 use sqlx::PgPool;
+use uuid::Uuid;
+use crate::services::search_engine::{self, SearchEngine};
+
+pub use pool::PoolConfig;
+pub use replica::{ReadConsistency, ReadReplica, ReplicaStatus};
 
 #[derive(Debug, Clone)]
 pub struct DatabaseConfig {
@@ -46,6 +55,13 @@ pub struct AppConfig {
     pub server_port: u16,
     pub cors_origins: Vec<String>,
     pub database_pool: PgPool,
+    /// Pool sizes, timeouts and the public endpoint query budget
+    pub pool: PoolConfig,
+    /// Read-only replica for heavy reads; None when DATABASE_REPLICA_HOST is unset
+    pub read_replica: Option<ReadReplica>,
+    /// External search engine for marketplace and catalog search; None when
+    /// SEARCH_ENGINE is unset, in which case Postgres answers every search
+    pub search_engine: Option<Arc<dyn SearchEngine>>,
     pub file_storage_path: String,
 }
 
@@ -62,35 +78,26 @@ impl AppConfig {
         let database_config = DatabaseConfig::from_env()?;
 
         // 🔒 PRODUCTION DATABASE CONNECTION POOL
-        // Configure connection pooling to prevent resource exhaustion
-        use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
-        use std::str::FromStr;
-
-        // Parse connection options
-        let mut connect_opts = PgConnectOptions::from_str(&database_config.connection_string())?;
-
-        // 🔒 SECURITY: Set statement timeout (query-level timeout)
-        // Prevents long-running queries from blocking the application
-        connect_opts = connect_opts
-            .statement_cache_capacity(100)  // Cache prepared statements
-            .application_name("atlas_pharma");  // Identify in pg_stat_activity
-
-        // Add statement timeout via connection string
-        let connection_string_with_timeout = format!(
-            "{}&options=-c%20statement_timeout=30000",  // 30 second query timeout
-            database_config.connection_string()
-        );
-
-        let database_pool = PgPoolOptions::new()
-            .max_connections(30)  // Maximum 30 concurrent connections (prevents database overload)
-            .min_connections(5)   // Maintain 5 idle connections (reduces connection overhead)
-            .acquire_timeout(std::time::Duration::from_secs(10))  // 10s timeout to acquire connection
-            .idle_timeout(std::time::Duration::from_secs(600))    // Close idle connections after 10 minutes
-            .max_lifetime(std::time::Duration::from_secs(1800))   // Recycle connections after 30 minutes
-            .connect(&connection_string_with_timeout)
+        // Sized and timed out from DATABASE_POOL_* (see config::pool) to prevent resource
+        // exhaustion; every connection gets a statement timeout and logs slow queries
+        let pool_config = PoolConfig::from_env()?;
+        let database_pool = pool_config
+            .pool_options()
+            .connect_with(pool_config.connect_options(&database_config, "atlas_pharma")?)
             .await?;
 
-        tracing::info!("✅ Database connection pool initialized (max: 30, min: 5)");
+        tracing::info!(
+            "✅ Database connection pool initialized (max: {}, min: {}, statement timeout: {}ms)",
+            pool_config.max_connections,
+            pool_config.min_connections,
+            pool_config.statement_timeout.as_millis()
+        );
+
+        let read_replica = DatabaseConfig::replica_from_env(&database_config)?
+            .map(|replica_config| {
+                ReadReplica::connect(&replica_config, &PoolConfig::replica_from_env(&pool_config)?)
+            })
+            .transpose()?;
 
         Ok(Self {
             database: database_config,
@@ -103,11 +110,31 @@ impl AppConfig {
                 .unwrap_or(8080),
             cors_origins,
             database_pool,
+            pool: pool_config,
+            read_replica,
+            search_engine: search_engine::from_env()?,
             file_storage_path: env::var("FILE_STORAGE_PATH")
                 .unwrap_or_else(|_| "./uploads".to_string()),
         })
     }
 
+    /// Pool for a heavy read: the replica when one is configured and fresh enough for
+    /// `consistency`, the primary otherwise. Never use it for writes.
+    pub fn read_pool(&self, consistency: ReadConsistency) -> PgPool {
+        self.read_replica
+            .as_ref()
+            .and_then(|replica| replica.pool_for(consistency))
+            .unwrap_or(&self.database_pool)
+            .clone()
+    }
+
+    /// Send the user's reads on read-after-write endpoints to the primary for a while
+    pub fn note_write(&self, user_id: Uuid) {
+        if let Some(replica) = &self.read_replica {
+            replica.note_write(user_id);
+        }
+    }
+
     pub fn server_address(&self) -> String {
         format!("{}:{}", self.server_host, self.server_port)
     }
@@ -5,6 +5,7 @@
 
 use std::env;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use thiserror::Error;
 
 /// OAuth configuration errors
@@ -313,12 +314,12 @@ impl Default for OAuthConfig {
 }
 
 /// Information about enabled OAuth providers (for frontend)
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, ToSchema)]
 pub struct OAuthProvidersInfo {
     pub providers: Vec<ProviderInfo>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, ToSchema)]
 pub struct ProviderInfo {
     pub name: String,
     pub display_name: String,
@@ -0,0 +1,218 @@
+/// Database connection pool tuning
+///
+/// Pool size, timeouts and slow-query logging of the primary pool come from
+/// `DATABASE_POOL_*` / `DATABASE_*` variables; the read replica pool takes its own
+/// size and acquire timeout from `DATABASE_REPLICA_POOL_*` and shares the rest.
+///
+/// Configuration (defaults in parentheses):
+/// - DATABASE_POOL_MAX_CONNECTIONS (30), DATABASE_POOL_MIN_CONNECTIONS (5)
+/// - DATABASE_POOL_ACQUIRE_TIMEOUT_MS (10000): wait for a free connection before the
+///   request fails with 503
+/// - DATABASE_POOL_IDLE_TIMEOUT_SECONDS (600), DATABASE_POOL_MAX_LIFETIME_SECONDS (1800)
+/// - DATABASE_STATEMENT_TIMEOUT_MS (30000): Postgres `statement_timeout` of every connection
+/// - DATABASE_SLOW_QUERY_MS (1000): statements at least this slow are logged as warnings
+/// - PUBLIC_QUERY_BUDGET_MS (5000): time a public endpoint may spend per request
+///   (see `middleware::query_budget`)
+/// - DATABASE_REPLICA_POOL_MAX_CONNECTIONS (20), DATABASE_REPLICA_POOL_ACQUIRE_TIMEOUT_MS (5000)
+///
+/// `run_pool_monitor` exports pool saturation (in-use share of the maximum) as metrics
+/// and warns while a pool stays nearly exhausted.
+
+use anyhow::{bail, Result};
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use sqlx::{ConnectOptions, PgPool};
+use std::env;
+use std::str::FromStr;
+use std::time::Duration;
+use super::{AppConfig, DatabaseConfig};
+use crate::middleware::metrics;
+
+/// How often pool saturation is sampled
+const POOL_MONITOR_INTERVAL: Duration = Duration::from_secs(5);
+
+/// In-use share of the pool above which it counts as saturated
+const SATURATION_WARN_RATIO: f64 = 0.9;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoolConfig {
+    pub max_connections: u32,
+    pub min_connections: u32,
+    pub acquire_timeout: Duration,
+    pub idle_timeout: Duration,
+    pub max_lifetime: Duration,
+    pub statement_timeout: Duration,
+    pub slow_query_threshold: Duration,
+    /// Time budget of a request to a public endpoint
+    pub public_query_budget: Duration,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            max_connections: 30,
+            min_connections: 5,
+            acquire_timeout: Duration::from_secs(10),
+            idle_timeout: Duration::from_secs(600),
+            max_lifetime: Duration::from_secs(1800),
+            statement_timeout: Duration::from_secs(30),
+            slow_query_threshold: Duration::from_secs(1),
+            public_query_budget: Duration::from_secs(5),
+        }
+    }
+}
+
+impl PoolConfig {
+    /// Primary pool settings from the environment
+    pub fn from_env() -> Result<Self> {
+        let defaults = Self::default();
+        let config = Self {
+            max_connections: env_or("DATABASE_POOL_MAX_CONNECTIONS", defaults.max_connections)?,
+            min_connections: env_or("DATABASE_POOL_MIN_CONNECTIONS", defaults.min_connections)?,
+            acquire_timeout: env_ms("DATABASE_POOL_ACQUIRE_TIMEOUT_MS", defaults.acquire_timeout)?,
+            idle_timeout: env_secs("DATABASE_POOL_IDLE_TIMEOUT_SECONDS", defaults.idle_timeout)?,
+            max_lifetime: env_secs("DATABASE_POOL_MAX_LIFETIME_SECONDS", defaults.max_lifetime)?,
+            statement_timeout: env_ms("DATABASE_STATEMENT_TIMEOUT_MS", defaults.statement_timeout)?,
+            slow_query_threshold: env_ms("DATABASE_SLOW_QUERY_MS", defaults.slow_query_threshold)?,
+            public_query_budget: env_ms("PUBLIC_QUERY_BUDGET_MS", defaults.public_query_budget)?,
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Replica pool settings: its own size and acquire timeout, the primary's otherwise.
+    /// Connections open lazily, so it keeps no idle connections.
+    pub fn replica_from_env(primary: &PoolConfig) -> Result<Self> {
+        let config = Self {
+            max_connections: env_or("DATABASE_REPLICA_POOL_MAX_CONNECTIONS", 20)?,
+            min_connections: 0,
+            acquire_timeout: env_ms("DATABASE_REPLICA_POOL_ACQUIRE_TIMEOUT_MS", Duration::from_secs(5))?,
+            ..primary.clone()
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.max_connections == 0 {
+            bail!("Database pool max connections must be at least 1");
+        }
+        if self.min_connections > self.max_connections {
+            bail!(
+                "Database pool min connections ({}) exceed max connections ({})",
+                self.min_connections,
+                self.max_connections
+            );
+        }
+        if self.statement_timeout.is_zero() || self.public_query_budget.is_zero() {
+            bail!("Statement timeout and public query budget must be positive");
+        }
+        Ok(())
+    }
+
+    /// Connection options: statement timeout and slow-query logging on every connection
+    pub fn connect_options(&self, database: &DatabaseConfig, application_name: &str) -> Result<PgConnectOptions> {
+        Ok(PgConnectOptions::from_str(&database.connection_string())?
+            .statement_cache_capacity(100)
+            .application_name(application_name)
+            .options([("statement_timeout", self.statement_timeout.as_millis().to_string())])
+            .log_slow_statements(log::LevelFilter::Warn, self.slow_query_threshold))
+    }
+
+    pub fn pool_options(&self) -> PgPoolOptions {
+        PgPoolOptions::new()
+            .max_connections(self.max_connections)
+            .min_connections(self.min_connections)
+            .acquire_timeout(self.acquire_timeout)
+            .idle_timeout(self.idle_timeout)
+            .max_lifetime(self.max_lifetime)
+    }
+}
+
+fn env_or<T: FromStr>(name: &str, default: T) -> Result<T>
+where
+    T::Err: std::fmt::Display,
+{
+    match env::var(name) {
+        Ok(value) => value
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Invalid {}={}: {}", name, value, e)),
+        Err(_) => Ok(default),
+    }
+}
+
+fn env_ms(name: &str, default: Duration) -> Result<Duration> {
+    env_or(name, default.as_millis() as u64).map(Duration::from_millis)
+}
+
+fn env_secs(name: &str, default: Duration) -> Result<Duration> {
+    env_or(name, default.as_secs()).map(Duration::from_secs)
+}
+
+/// Sample pool saturation of the primary (and replica) every POOL_MONITOR_INTERVAL
+/// until the process exits
+pub async fn run_pool_monitor(config: AppConfig) {
+    let mut interval = tokio::time::interval(POOL_MONITOR_INTERVAL);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let mut saturated = [false; 2];
+
+    loop {
+        interval.tick().await;
+
+        let replica = config.read_replica.as_ref().map(|replica| replica.pool());
+        let pools = [("primary", Some(&config.database_pool)), ("replica", replica)];
+        for ((name, pool), was_saturated) in pools.into_iter().zip(saturated.iter_mut()) {
+            let Some(pool) = pool else { continue };
+            let is_saturated = sample_pool(name, pool) >= SATURATION_WARN_RATIO;
+
+            if is_saturated && !*was_saturated {
+                tracing::warn!(
+                    "🗄️  Database pool '{}' saturated: {} of {} connections in use",
+                    name,
+                    (pool.size() as usize).saturating_sub(pool.num_idle()),
+                    pool.options().get_max_connections()
+                );
+            } else if !is_saturated && *was_saturated {
+                tracing::info!("🗄️  Database pool '{}' no longer saturated", name);
+            }
+            *was_saturated = is_saturated;
+        }
+    }
+}
+
+/// Record a pool's state; returns the in-use share of its maximum
+fn sample_pool(name: &str, pool: &PgPool) -> f64 {
+    let max_connections = pool.options().get_max_connections();
+    let size = pool.size();
+    let idle = pool.num_idle();
+    metrics::record_db_pool_state(name, max_connections, size, idle);
+    saturation(max_connections, size, idle)
+}
+
+fn saturation(max_connections: u32, size: u32, idle: usize) -> f64 {
+    if max_connections == 0 {
+        return 0.0;
+    }
+    size.saturating_sub(idle as u32) as f64 / max_connections as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_rejects_inconsistent_pools() {
+        assert!(PoolConfig::default().validate().is_ok());
+
+        let config = PoolConfig { min_connections: 40, ..PoolConfig::default() };
+        assert!(config.validate().is_err());
+
+        let config = PoolConfig { max_connections: 0, min_connections: 0, ..PoolConfig::default() };
+        assert!(config.validate().is_err());
+
+        let config = PoolConfig { public_query_budget: Duration::ZERO, ..PoolConfig::default() };
+        assert!(config.validate().is_err());
+
+        assert_eq!(saturation(30, 30, 3), 0.9);
+        assert_eq!(saturation(0, 0, 0), 0.0);
+    }
+}
@@ -0,0 +1,449 @@
+/// Startup preflight
+///
+/// Runs before the server binds, so a misconfigured instance refuses to boot listing
+/// every problem at once instead of failing on its first request:
+/// - `check_environment`: required variables are set and parse, JWT_SECRET and
+///   ENCRYPTION_KEY are long and random enough, and the TLS certificate (when enabled)
+///   is valid for at least another TLS_CERT_EXPIRY_WARN_DAYS (14) days, or only warns
+/// - `check_migrations`: every file in MIGRATIONS_DIR (./migrations) is recorded in
+///   `schema_migrations`. DATABASE_MIGRATIONS selects what happens otherwise: `check`
+///   (default) refuses to boot, `run` applies the pending files in name order (one
+///   instance at a time, under an advisory lock), `off` skips the check.
+///
+/// Migrations are tracked by file name rather than in sqlx's `_sqlx_migrations`:
+/// versions 007 and 008 each name two files, and existing databases were migrated
+/// with psql (migration 091 records those as applied).
+
+use anyhow::{bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use chrono::{Duration, Utc};
+use sha2::{Digest, Sha256};
+use sqlx::{Connection, Executor, PgConnection, PgPool};
+use std::collections::HashMap;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use super::{tls::TlsConfig, DatabaseConfig, PoolConfig};
+use crate::services::search_engine;
+
+/// Variables the server can't start without
+const REQUIRED_VARS: [&str; 3] = ["DATABASE_PASSWORD", "JWT_SECRET", "ENCRYPTION_KEY"];
+
+/// Estimated entropy a secret needs: catches placeholders and repeated patterns, it
+/// can't prove a secret is random
+const MIN_SECRET_ENTROPY_BITS: f64 = 128.0;
+const MIN_JWT_SECRET_BYTES: usize = 32;
+
+const DEFAULT_CERT_EXPIRY_WARN_DAYS: i64 = 14;
+
+/// Advisory lock held while migrations run, so instances starting together apply
+/// each file once
+const MIGRATION_LOCK_ID: i64 = 0x6174_6c61_735f_6d67;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationMode {
+    Check,
+    Run,
+    Off,
+}
+
+impl MigrationMode {
+    pub fn from_env() -> Result<Self> {
+        match env::var("DATABASE_MIGRATIONS").unwrap_or_default().trim().to_lowercase().as_str() {
+            "" | "check" => Ok(Self::Check),
+            "run" => Ok(Self::Run),
+            "off" => Ok(Self::Off),
+            other => bail!("Unsupported DATABASE_MIGRATIONS '{}' (supported: check, run, off)", other),
+        }
+    }
+}
+
+/// What the preflight found that doesn't stop the server; logged once logging is up
+#[derive(Debug, Default)]
+pub struct PreflightReport {
+    pub warnings: Vec<String>,
+    /// Migration files applied by this start (DATABASE_MIGRATIONS=run)
+    pub applied_migrations: Vec<String>,
+    pub migration_mode: Option<MigrationMode>,
+    pub migration_count: usize,
+}
+
+impl PreflightReport {
+    pub fn log(&self) {
+        for warning in &self.warnings {
+            tracing::warn!("⚠️  Preflight: {}", warning);
+        }
+        for filename in &self.applied_migrations {
+            tracing::info!("🗄️  Applied migration {}", filename);
+        }
+        match self.migration_mode {
+            Some(MigrationMode::Off) => tracing::warn!("⚠️  Migration check disabled (DATABASE_MIGRATIONS=off)"),
+            Some(_) => tracing::info!("🗄️  Database schema up to date ({} migrations)", self.migration_count),
+            None => {}
+        }
+    }
+}
+
+/// Every problem found, one per line
+#[derive(Debug, Default)]
+struct Problems(Vec<String>);
+
+impl Problems {
+    fn check<T>(&mut self, what: &str, result: Result<T>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(e) => {
+                self.0.push(format!("{}: {:#}", what, e));
+                None
+            }
+        }
+    }
+
+    fn finish(self) -> Result<()> {
+        if self.0.is_empty() {
+            return Ok(());
+        }
+        bail!(
+            "Startup preflight failed ({} problems):\n  - {}",
+            self.0.len(),
+            self.0.join("\n  - ")
+        )
+    }
+}
+
+/// Check the environment, failing with every problem found
+pub fn check_environment() -> Result<PreflightReport> {
+    dotenvy::dotenv().ok();
+
+    let mut report = PreflightReport::default();
+    let mut problems = Problems::default();
+
+    for name in REQUIRED_VARS {
+        if env::var(name).map(|value| value.trim().is_empty()).unwrap_or(true) {
+            problems.0.push(format!("{} must be set", name));
+        }
+    }
+    for name in ["DATABASE_PORT", "SERVER_PORT"] {
+        problems.check(name, parse_if_set::<u16>(name));
+    }
+
+    let jwt_secret = env::var("JWT_SECRET").ok().filter(|secret| !secret.trim().is_empty());
+    let encryption_key = env::var("ENCRYPTION_KEY").ok().filter(|key| !key.trim().is_empty());
+    if let Some(secret) = &jwt_secret {
+        problems.check("JWT_SECRET", check_jwt_secret(secret));
+    }
+    if let Some(key) = &encryption_key {
+        problems.check("ENCRYPTION_KEY", check_encryption_key(key));
+    }
+    if jwt_secret.is_some() && jwt_secret == encryption_key {
+        problems.0.push("JWT_SECRET and ENCRYPTION_KEY must differ".to_string());
+    }
+
+    let pool = problems.check("Database pool", PoolConfig::from_env());
+    if env::var("DATABASE_PASSWORD").is_ok() {
+        if let Some(database) = problems.check("Database", DatabaseConfig::from_env()) {
+            problems.check("Read replica", DatabaseConfig::replica_from_env(&database));
+        }
+    }
+    if let Some(pool) = &pool {
+        problems.check("Read replica pool", PoolConfig::replica_from_env(pool));
+    }
+    problems.check("Search engine", search_engine::from_env());
+    problems.check("DATABASE_MIGRATIONS", MigrationMode::from_env());
+
+    if let Some(tls) = problems.check("TLS", TlsConfig::from_env()) {
+        if tls.enabled {
+            check_certificate(&tls, &mut problems, &mut report);
+        }
+    }
+
+    problems.finish()?;
+    Ok(report)
+}
+
+/// Check (or with DATABASE_MIGRATIONS=run, apply) the migrations in MIGRATIONS_DIR
+pub async fn check_migrations(pool: &PgPool, report: &mut PreflightReport) -> Result<()> {
+    let mode = MigrationMode::from_env()?;
+    report.migration_mode = Some(mode);
+    if mode == MigrationMode::Off {
+        return Ok(());
+    }
+
+    let dir = PathBuf::from(env::var("MIGRATIONS_DIR").unwrap_or_else(|_| "./migrations".to_string()));
+    let files = migration_files(&dir)?;
+    report.migration_count = files.len();
+
+    let ledger_exists: bool = sqlx::query_scalar("SELECT to_regclass('public.schema_migrations') IS NOT NULL")
+        .fetch_one(pool)
+        .await?;
+    if !ledger_exists {
+        let has_schema: bool = sqlx::query_scalar("SELECT to_regclass('public.users') IS NOT NULL")
+            .fetch_one(pool)
+            .await?;
+        if has_schema {
+            bail!(
+                "Database has no schema_migrations table: it was migrated with psql. Apply the \
+                 pending files in {} with psql (091_schema_migrations.sql records the earlier ones)",
+                dir.display()
+            );
+        }
+        if mode == MigrationMode::Check {
+            bail!("Database is empty: apply the files in {} or start with DATABASE_MIGRATIONS=run", dir.display());
+        }
+    }
+
+    let applied = if ledger_exists { applied_migrations(pool).await? } else { HashMap::new() };
+    for file in &files {
+        if let Some(Some(checksum)) = applied.get(&file.filename) {
+            if *checksum != file.checksum {
+                report.warnings.push(format!("Migration {} changed after it was applied", file.filename));
+            }
+        }
+    }
+
+    let pending: Vec<&MigrationFile> = files.iter().filter(|file| !applied.contains_key(&file.filename)).collect();
+    if pending.is_empty() {
+        return Ok(());
+    }
+    if mode == MigrationMode::Check {
+        bail!(
+            "{} pending migrations: {} (apply them, or start with DATABASE_MIGRATIONS=run)",
+            pending.len(),
+            pending.iter().map(|file| file.filename.as_str()).collect::<Vec<_>>().join(", ")
+        );
+    }
+
+    report.applied_migrations = run_migrations(pool, &files).await?;
+    Ok(())
+}
+
+// ============================================================================
+// SECRETS
+// ============================================================================
+
+fn check_jwt_secret(secret: &str) -> Result<()> {
+    if looks_like_placeholder(secret) {
+        bail!("still the example placeholder; generate one with `openssl rand -base64 64`");
+    }
+    if secret.len() < MIN_JWT_SECRET_BYTES {
+        bail!("must be at least {} bytes (is {})", MIN_JWT_SECRET_BYTES, secret.len());
+    }
+    check_entropy(secret.as_bytes())
+}
+
+fn check_encryption_key(key: &str) -> Result<()> {
+    if looks_like_placeholder(key) {
+        bail!("still the example placeholder; generate one with `openssl rand -base64 32`");
+    }
+    let bytes = BASE64.decode(key.trim()).map_err(|_| anyhow::anyhow!("must be base64"))?;
+    if bytes.len() != 32 {
+        bail!("must decode to 32 bytes (decodes to {})", bytes.len());
+    }
+    check_entropy(&bytes)
+}
+
+fn looks_like_placeholder(secret: &str) -> bool {
+    let upper = secret.to_uppercase();
+    upper.contains("YOUR_") || upper.contains("CHANGE_ME") || upper.contains("CHANGEME")
+}
+
+fn check_entropy(bytes: &[u8]) -> Result<()> {
+    let bits = estimated_entropy_bits(bytes);
+    if bits < MIN_SECRET_ENTROPY_BITS {
+        bail!(
+            "not random enough (estimated {:.0} bits of entropy, needs {:.0})",
+            bits,
+            MIN_SECRET_ENTROPY_BITS
+        );
+    }
+    Ok(())
+}
+
+/// Shannon entropy of the byte distribution times the length
+fn estimated_entropy_bits(bytes: &[u8]) -> f64 {
+    let mut counts = [0usize; 256];
+    for byte in bytes {
+        counts[*byte as usize] += 1;
+    }
+    let len = bytes.len() as f64;
+    let bits_per_byte: f64 = counts
+        .iter()
+        .filter(|count| **count > 0)
+        .map(|count| {
+            let p = *count as f64 / len;
+            -p * p.log2()
+        })
+        .sum();
+    bits_per_byte * len
+}
+
+fn parse_if_set<T: FromStr>(name: &str) -> Result<()> {
+    match env::var(name) {
+        Ok(value) if value.trim().parse::<T>().is_err() => bail!("invalid value '{}'", value),
+        _ => Ok(()),
+    }
+}
+
+// ============================================================================
+// TLS
+// ============================================================================
+
+fn check_certificate(tls: &TlsConfig, problems: &mut Problems, report: &mut PreflightReport) {
+    if !tls.key_path.exists() {
+        problems.0.push(format!("TLS: private key not found at {}", tls.key_path.display()));
+    }
+    let Some((not_before, not_after)) = problems.check("TLS certificate", tls.certificate_validity()) else {
+        return;
+    };
+
+    let now = Utc::now();
+    let warn_days = env::var("TLS_CERT_EXPIRY_WARN_DAYS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_CERT_EXPIRY_WARN_DAYS);
+    if not_after <= now {
+        problems.0.push(format!("TLS certificate {} expired on {}", tls.cert_path.display(), not_after));
+    } else if not_before > now {
+        problems.0.push(format!("TLS certificate {} is not valid until {}", tls.cert_path.display(), not_before));
+    } else if not_after <= now + Duration::days(warn_days) {
+        report.warnings.push(format!(
+            "TLS certificate {} expires on {} ({} days left)",
+            tls.cert_path.display(),
+            not_after,
+            (not_after - now).num_days()
+        ));
+    }
+}
+
+// ============================================================================
+// MIGRATIONS
+// ============================================================================
+
+struct MigrationFile {
+    filename: String,
+    path: PathBuf,
+    checksum: String,
+}
+
+/// The `.sql` files of the directory, in name order
+fn migration_files(dir: &Path) -> Result<Vec<MigrationFile>> {
+    let entries = std::fs::read_dir(dir).with_context(|| {
+        format!("Migrations directory {} not found (set MIGRATIONS_DIR, or DATABASE_MIGRATIONS=off)", dir.display())
+    })?;
+
+    let mut files = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("sql") {
+            continue;
+        }
+        let Some(filename) = path.file_name().and_then(|name| name.to_str()).map(str::to_string) else {
+            continue;
+        };
+        let sql = std::fs::read(&path).with_context(|| format!("Failed to read migration {}", filename))?;
+        files.push(MigrationFile { filename, path, checksum: hex::encode(Sha256::digest(&sql)) });
+    }
+    files.sort_by(|a, b| a.filename.cmp(&b.filename));
+    Ok(files)
+}
+
+/// Applied file names and their checksums
+async fn applied_migrations(pool: &PgPool) -> Result<HashMap<String, Option<String>>> {
+    let rows: Vec<(String, Option<String>)> = sqlx::query_as("SELECT filename, checksum FROM schema_migrations")
+        .fetch_all(pool)
+        .await?;
+    Ok(rows.into_iter().collect())
+}
+
+/// Apply the files not yet recorded; returns their names
+async fn run_migrations(pool: &PgPool, files: &[MigrationFile]) -> Result<Vec<String>> {
+    // A connection of its own: migrations may run past the pool's statement timeout
+    let mut conn = PgConnection::connect_with(&pool.connect_options()).await?;
+    conn.execute("SET statement_timeout = 0").await?;
+    sqlx::query("SELECT pg_advisory_lock($1)").bind(MIGRATION_LOCK_ID).execute(&mut conn).await?;
+
+    let result = apply_pending(&mut conn, files).await;
+
+    let _ = sqlx::query("SELECT pg_advisory_unlock($1)").bind(MIGRATION_LOCK_ID).execute(&mut conn).await;
+    let _ = conn.close().await;
+    result
+}
+
+async fn apply_pending(conn: &mut PgConnection, files: &[MigrationFile]) -> Result<Vec<String>> {
+    (&mut *conn).execute(
+        r#"
+        CREATE TABLE IF NOT EXISTS schema_migrations (
+            filename TEXT PRIMARY KEY,
+            checksum VARCHAR(64),
+            execution_ms BIGINT,
+            applied_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+        )
+        "#,
+    )
+    .await?;
+
+    // Re-read under the lock: another instance may have migrated meanwhile
+    let applied: Vec<String> = sqlx::query_scalar("SELECT filename FROM schema_migrations")
+        .fetch_all(&mut *conn)
+        .await?;
+
+    let mut applied_now = Vec::new();
+    for file in files.iter().filter(|file| !applied.contains(&file.filename)) {
+        let sql = std::fs::read_to_string(&file.path)
+            .with_context(|| format!("Failed to read migration {}", file.filename))?;
+        let started = std::time::Instant::now();
+
+        // Unprepared, so a file may hold several statements (as with psql -f)
+        (&mut *conn)
+            .execute(sql.as_str())
+            .await
+            .with_context(|| format!("Migration {} failed", file.filename))?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO schema_migrations (filename, checksum, execution_ms)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (filename) DO UPDATE
+            SET checksum = EXCLUDED.checksum, execution_ms = EXCLUDED.execution_ms, applied_at = NOW()
+            "#,
+        )
+        .bind(&file.filename)
+        .bind(&file.checksum)
+        .bind(started.elapsed().as_millis() as i64)
+        .execute(&mut *conn)
+        .await?;
+        applied_now.push(file.filename.clone());
+    }
+    Ok(applied_now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secrets_need_length_and_entropy() {
+        assert!(check_jwt_secret("YOUR_JWT_SECRET_HERE").is_err());
+        assert!(check_jwt_secret("short-secret").is_err());
+        assert!(check_jwt_secret(&"ab".repeat(40)).is_err());
+        assert!(check_jwt_secret("kq3R9vXz2LmN8pT4wY7bC1dF6gH0jK5sU/eA+oQiVxBnMrPtZyWc").is_ok());
+
+        assert!(check_encryption_key("YOUR_ENCRYPTION_KEY_HERE").is_err());
+        assert!(check_encryption_key(&BASE64.encode([7u8; 32])).is_err());
+        assert!(check_encryption_key(&BASE64.encode([1u8; 16])).is_err());
+        let key: Vec<u8> = (0..32u8).map(|i| i.wrapping_mul(37).wrapping_add(11)).collect();
+        assert!(check_encryption_key(&BASE64.encode(key)).is_ok());
+    }
+
+    #[test]
+    fn test_problems_are_aggregated() {
+        let mut problems = Problems::default();
+        problems.check::<()>("JWT_SECRET", Err(anyhow::anyhow!("too short")));
+        problems.0.push("ENCRYPTION_KEY must be set".to_string());
+
+        let message = problems.finish().unwrap_err().to_string();
+        assert!(message.contains("2 problems"));
+        assert!(message.contains("JWT_SECRET: too short"));
+        assert!(message.contains("ENCRYPTION_KEY must be set"));
+    }
+}
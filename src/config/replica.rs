@@ -0,0 +1,275 @@
+/// Read replica routing
+///
+/// With `DATABASE_REPLICA_HOST` set, heavy read paths (catalog search, analytics,
+/// exports, admin stats) query a read-only replica through `AppConfig::read_pool`.
+/// Everything else, and every write, stays on the primary.
+///
+/// The lag monitor compares the primary's WAL position with the replica's replay
+/// position every few seconds. Reads fall back to the primary while the replica is
+/// unreachable, not yet checked, or more than `DATABASE_REPLICA_MAX_LAG_SECONDS`
+/// behind. Read-after-write endpoints additionally send users who wrote within that
+/// window to the primary, so they see their own changes.
+
+use dashmap::DashMap;
+use sqlx::PgPool;
+use std::env;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+use super::{DatabaseConfig, PoolConfig};
+use crate::middleware::metrics;
+
+/// How often replica lag is measured
+const LAG_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Time a lag check may take before the replica counts as unreachable
+const LAG_CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// How stale a read may be
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadConsistency {
+    /// Replica while its lag is within the limit
+    Relaxed,
+    /// Like `Relaxed`, but the primary for a user who wrote within the lag limit
+    ReadYourWrites(Uuid),
+}
+
+impl DatabaseConfig {
+    /// Replica connection from `DATABASE_REPLICA_*`; port, credentials and database
+    /// default to the primary's. None without `DATABASE_REPLICA_HOST`.
+    pub fn replica_from_env(primary: &DatabaseConfig) -> anyhow::Result<Option<Self>> {
+        let Ok(host) = env::var("DATABASE_REPLICA_HOST") else {
+            return Ok(None);
+        };
+
+        Ok(Some(Self {
+            host,
+            port: match env::var("DATABASE_REPLICA_PORT") {
+                Ok(port) => port.parse()?,
+                Err(_) => primary.port,
+            },
+            username: env::var("DATABASE_REPLICA_USER").unwrap_or_else(|_| primary.username.clone()),
+            password: env::var("DATABASE_REPLICA_PASSWORD").unwrap_or_else(|_| primary.password.clone()),
+            database: primary.database.clone(),
+            ssl_mode: primary.ssl_mode.clone(),
+        }))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ReadReplica {
+    pool: PgPool,
+    state: Arc<ReplicaState>,
+}
+
+#[derive(Debug)]
+struct ReplicaState {
+    max_lag: Duration,
+    /// False until the first successful lag check, and while checks fail
+    reachable: AtomicBool,
+    lag_ms: AtomicU64,
+    /// Users' latest writes, for `ReadConsistency::ReadYourWrites`
+    recent_writers: DashMap<Uuid, Instant>,
+}
+
+/// Replica status for health checks
+#[derive(Debug, Clone, Copy)]
+pub struct ReplicaStatus {
+    pub reachable: bool,
+    pub lag: Duration,
+    pub max_lag: Duration,
+    pub in_use: bool,
+}
+
+impl ReadReplica {
+    /// Pool for the replica. Connections open lazily, so an unreachable replica
+    /// doesn't stop startup; reads use the primary until the lag monitor reaches it.
+    pub fn connect(config: &DatabaseConfig, pool_config: &PoolConfig) -> anyhow::Result<Self> {
+        let max_lag = env::var("DATABASE_REPLICA_MAX_LAG_SECONDS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(5);
+
+        let pool = pool_config
+            .pool_options()
+            .connect_lazy_with(pool_config.connect_options(config, "atlas_pharma_replica")?);
+
+        tracing::info!(
+            "✅ Read replica pool configured ({}:{}, max lag {}s)",
+            config.host,
+            config.port,
+            max_lag
+        );
+
+        Ok(Self {
+            pool,
+            state: Arc::new(ReplicaState {
+                max_lag: Duration::from_secs(max_lag),
+                reachable: AtomicBool::new(false),
+                lag_ms: AtomicU64::new(0),
+                recent_writers: DashMap::new(),
+            }),
+        })
+    }
+
+    pub(crate) fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+
+    /// The replica pool, or None when reads of this consistency must use the primary
+    pub fn pool_for(&self, consistency: ReadConsistency) -> Option<&PgPool> {
+        let (target, reason) = self.route(consistency);
+        metrics::record_read_routing(target, reason);
+        (target == "replica").then_some(&self.pool)
+    }
+
+    fn route(&self, consistency: ReadConsistency) -> (&'static str, &'static str) {
+        if !self.state.reachable.load(Ordering::Relaxed) {
+            return ("primary", "replica_unreachable");
+        }
+        if self.lag() > self.state.max_lag {
+            return ("primary", "replica_lagging");
+        }
+        if let ReadConsistency::ReadYourWrites(user_id) = consistency {
+            let wrote_recently = self
+                .state
+                .recent_writers
+                .get(&user_id)
+                .is_some_and(|wrote_at| wrote_at.elapsed() <= self.write_window());
+            if wrote_recently {
+                return ("primary", "recent_write");
+            }
+        }
+        ("replica", "ok")
+    }
+
+    /// Remember that a user wrote to the primary
+    pub fn note_write(&self, user_id: Uuid) {
+        self.state.recent_writers.insert(user_id, Instant::now());
+    }
+
+    /// Writes older than this are on the replica whenever it is used: its lag was
+    /// within `max_lag` at the latest check, at most one check interval ago
+    fn write_window(&self) -> Duration {
+        self.state.max_lag + LAG_CHECK_INTERVAL
+    }
+
+    fn lag(&self) -> Duration {
+        Duration::from_millis(self.state.lag_ms.load(Ordering::Relaxed))
+    }
+
+    pub fn status(&self) -> ReplicaStatus {
+        let reachable = self.state.reachable.load(Ordering::Relaxed);
+        ReplicaStatus {
+            reachable,
+            lag: self.lag(),
+            max_lag: self.state.max_lag,
+            in_use: reachable && self.lag() <= self.state.max_lag,
+        }
+    }
+
+    /// Measure replica lag every LAG_CHECK_INTERVAL until the process exits
+    pub async fn run_lag_monitor(self, primary: PgPool) {
+        let mut interval = tokio::time::interval(LAG_CHECK_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+
+            let was_reachable = self.state.reachable.load(Ordering::Relaxed);
+            match tokio::time::timeout(LAG_CHECK_TIMEOUT, self.measure_lag(&primary)).await {
+                Ok(Ok(lag)) => {
+                    self.state.lag_ms.store(lag.as_millis() as u64, Ordering::Relaxed);
+                    self.state.reachable.store(true, Ordering::Relaxed);
+                    metrics::record_replica_lag(Some(lag));
+                    if !was_reachable {
+                        tracing::info!("Read replica reachable (lag {}ms); routing reads to it", lag.as_millis());
+                    } else if lag > self.state.max_lag {
+                        tracing::warn!(
+                            "Read replica is {}s behind (limit {}s); reads use the primary",
+                            lag.as_secs(),
+                            self.state.max_lag.as_secs()
+                        );
+                    }
+                }
+                Ok(Err(e)) => self.mark_unreachable(was_reachable, &e.to_string()),
+                Err(_) => self.mark_unreachable(was_reachable, "lag check timed out"),
+            }
+
+            let window = self.write_window();
+            self.state.recent_writers.retain(|_, wrote_at| wrote_at.elapsed() <= window);
+        }
+    }
+
+    fn mark_unreachable(&self, was_reachable: bool, error: &str) {
+        self.state.reachable.store(false, Ordering::Relaxed);
+        metrics::record_replica_lag(None);
+        if was_reachable {
+            tracing::error!("Read replica unreachable ({}); reads use the primary", error);
+        }
+    }
+
+    /// Zero once the replica has replayed everything the primary had written when the
+    /// check started; otherwise the age of the last replayed transaction. A replica
+    /// that isn't in recovery (pointed at the primary) has no lag.
+    async fn measure_lag(&self, primary: &PgPool) -> Result<Duration, sqlx::Error> {
+        let primary_lsn: String = sqlx::query_scalar("SELECT pg_current_wal_lsn()::text")
+            .fetch_one(primary)
+            .await?;
+
+        let seconds: f64 = sqlx::query_scalar(
+            r#"
+            SELECT CASE
+                WHEN NOT pg_is_in_recovery() THEN 0
+                WHEN pg_last_wal_replay_lsn() >= $1::pg_lsn THEN 0
+                ELSE COALESCE(EXTRACT(EPOCH FROM now() - pg_last_xact_replay_timestamp()), 0)
+            END::float8
+            "#
+        )
+        .bind(&primary_lsn)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(Duration::from_secs_f64(seconds.max(0.0)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replica() -> ReadReplica {
+        ReadReplica::connect(&DatabaseConfig {
+            host: "localhost".to_string(),
+            port: 5432,
+            username: "postgres".to_string(),
+            password: "postgres".to_string(),
+            database: "atlas_pharma".to_string(),
+            ssl_mode: "prefer".to_string(),
+        }, &PoolConfig { min_connections: 0, ..PoolConfig::default() })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_routing_falls_back_to_primary() {
+        let replica = replica();
+        let user_id = Uuid::new_v4();
+
+        // Not checked yet
+        assert!(replica.pool_for(ReadConsistency::Relaxed).is_none());
+
+        replica.state.reachable.store(true, Ordering::Relaxed);
+        assert!(replica.pool_for(ReadConsistency::Relaxed).is_some());
+        assert!(replica.pool_for(ReadConsistency::ReadYourWrites(user_id)).is_some());
+
+        replica.note_write(user_id);
+        assert!(replica.pool_for(ReadConsistency::ReadYourWrites(user_id)).is_none());
+        assert!(replica.pool_for(ReadConsistency::ReadYourWrites(Uuid::new_v4())).is_some());
+        assert!(replica.pool_for(ReadConsistency::Relaxed).is_some());
+
+        replica.state.lag_ms.store(60_000, Ordering::Relaxed);
+        assert!(replica.pool_for(ReadConsistency::Relaxed).is_none());
+        assert!(!replica.status().in_use);
+    }
+}
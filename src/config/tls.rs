@@ -11,6 +11,7 @@
 
 use anyhow::{Context, Result};
 use axum_server::tls_rustls::RustlsConfig;
+use chrono::{DateTime, Utc};
 use std::env;
 use std::path::PathBuf;
 
@@ -82,6 +83,23 @@ impl TlsConfig {
 
         Ok(config)
     }
+
+    /// Validity period (not before, not after) of the first certificate in the PEM file
+    pub fn certificate_validity(&self) -> Result<(DateTime<Utc>, DateTime<Utc>)> {
+        let pem_bytes = std::fs::read(&self.cert_path)
+            .with_context(|| format!("certificate not found at {:?}", self.cert_path))?;
+        let (_, pem) = x509_parser::pem::parse_x509_pem(&pem_bytes)
+            .map_err(|e| anyhow::anyhow!("{:?} is not a PEM certificate: {}", self.cert_path, e))?;
+        let certificate = pem
+            .parse_x509()
+            .map_err(|e| anyhow::anyhow!("{:?} is not a valid X.509 certificate: {}", self.cert_path, e))?;
+
+        let validity = certificate.validity();
+        let timestamp = |seconds: i64| {
+            DateTime::<Utc>::from_timestamp(seconds, 0).context("certificate validity out of range")
+        };
+        Ok((timestamp(validity.not_before.timestamp())?, timestamp(validity.not_after.timestamp())?))
+    }
 }
 
 /// Generate self-signed certificate for development
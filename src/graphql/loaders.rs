@@ -0,0 +1,96 @@
+// GraphQL DataLoaders
+// One loader per related type, created per request so nothing is cached across callers.
+// Keys requested while resolving one level of the response are batched into a single
+// `= ANY($1)` query.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use async_graphql::dataloader::{DataLoader, Loader};
+use async_graphql::Request;
+use uuid::Uuid;
+use crate::{
+    config::AppConfig,
+    middleware::error_handling::{AppError, Result},
+    models::{
+        inventory::Inventory,
+        marketplace::Inquiry,
+        openfda::OpenFdaCatalogEntry,
+        pharmaceutical::PharmaceuticalResponse,
+        user::UserResponse,
+    },
+    repositories::{InventoryRepository, MarketplaceRepository, OpenFdaRepository, PharmaceuticalRepository, UserRepository},
+};
+
+type LoadResult<K, V> = std::result::Result<HashMap<K, V>, Arc<AppError>>;
+
+pub struct UserLoader(UserRepository);
+
+impl Loader<Uuid> for UserLoader {
+    type Value = UserResponse;
+    type Error = Arc<AppError>;
+
+    async fn load(&self, ids: &[Uuid]) -> LoadResult<Uuid, UserResponse> {
+        let users = self.0.find_by_ids(ids).await?;
+        Ok(users.into_iter().map(|user| (user.id, user.into())).collect())
+    }
+}
+
+pub struct PharmaceuticalLoader(PharmaceuticalRepository);
+
+impl Loader<Uuid> for PharmaceuticalLoader {
+    type Value = PharmaceuticalResponse;
+    type Error = Arc<AppError>;
+
+    async fn load(&self, ids: &[Uuid]) -> LoadResult<Uuid, PharmaceuticalResponse> {
+        let pharmaceuticals = self.0.find_by_ids(ids).await?;
+        Ok(pharmaceuticals.into_iter().map(|pharma| (pharma.id, pharma.into())).collect())
+    }
+}
+
+pub struct InventoryLoader(InventoryRepository);
+
+impl Loader<Uuid> for InventoryLoader {
+    type Value = Inventory;
+    type Error = Arc<AppError>;
+
+    async fn load(&self, ids: &[Uuid]) -> LoadResult<Uuid, Inventory> {
+        let inventories = self.0.find_by_ids(ids).await?;
+        Ok(inventories.into_iter().map(|inventory| (inventory.id, inventory)).collect())
+    }
+}
+
+pub struct InquiryLoader(MarketplaceRepository);
+
+impl Loader<Uuid> for InquiryLoader {
+    type Value = Inquiry;
+    type Error = Arc<AppError>;
+
+    async fn load(&self, ids: &[Uuid]) -> LoadResult<Uuid, Inquiry> {
+        let inquiries = self.0.find_inquiries_by_ids(ids).await?;
+        Ok(inquiries.into_iter().map(|inquiry| (inquiry.id, inquiry)).collect())
+    }
+}
+
+/// OpenFDA catalog entries by product NDC (labeler-product)
+pub struct CatalogEntryLoader(OpenFdaRepository);
+
+impl Loader<String> for CatalogEntryLoader {
+    type Value = OpenFdaCatalogEntry;
+    type Error = Arc<AppError>;
+
+    async fn load(&self, product_ndcs: &[String]) -> LoadResult<String, OpenFdaCatalogEntry> {
+        let entries = self.0.find_by_ndcs(product_ndcs).await?;
+        Ok(entries.into_iter().map(|entry| (entry.product_ndc.clone(), entry)).collect())
+    }
+}
+
+/// Attach a fresh set of loaders to a request
+pub fn with_loaders(request: Request, config: &AppConfig) -> Result<Request> {
+    let pool = config.database_pool.clone();
+    Ok(request
+        .data(DataLoader::new(UserLoader(UserRepository::new(pool.clone(), &config.encryption_key)?), tokio::spawn))
+        .data(DataLoader::new(PharmaceuticalLoader(PharmaceuticalRepository::new(pool.clone())), tokio::spawn))
+        .data(DataLoader::new(InventoryLoader(InventoryRepository::new(pool.clone())), tokio::spawn))
+        .data(DataLoader::new(InquiryLoader(MarketplaceRepository::new(pool.clone())), tokio::spawn))
+        .data(DataLoader::new(CatalogEntryLoader(OpenFdaRepository::new(pool)), tokio::spawn)))
+}
@@ -0,0 +1,93 @@
+// GraphQL Module
+// The catalog, inventory, marketplace and notification types as one GraphQL schema, for
+// clients that want to fetch a screen's data in a single round trip (POST /graphql).
+//
+// - auth: the endpoint sits behind `auth_middleware` like the REST routes; resolvers
+//   read the caller's `Claims` from the request data
+// - authorization: root fields apply the same ownership/participant checks as the
+//   REST handlers; contact details, storage locations and ERP order numbers are
+//   checked per field (see `types`)
+// - N+1: nested users, pharmaceuticals, listings, inquiries and catalog entries go
+//   through per-request DataLoaders that batch each level into one query
+// - persisted queries: Apollo automatic persisted queries (the client sends the
+//   query's SHA-256 and only sends the text when the server doesn't know it yet)
+// - limits: query depth and complexity are capped so one request can't fan out into
+//   an unbounded number of loads
+//
+// Never `?` an `AppError` in a resolver: async-graphql would turn its Display text
+// (database and internal details) into the client message. Use `.gql()`, which
+// returns the client-safe message of the REST error body with the status as
+// `extensions.status`.
+
+pub mod loaders;
+pub mod query;
+pub mod types;
+
+use async_graphql::extensions::apollo_persisted_queries::{ApolloPersistedQueries, LruCacheStorage};
+use async_graphql::{Context, EmptySubscription, ErrorExtensions, Schema};
+use once_cell::sync::Lazy;
+use crate::middleware::{error_handling::AppError, Claims};
+
+pub use query::{MutationRoot, QueryRoot};
+
+pub type AtlasSchema = Schema<QueryRoot, MutationRoot, EmptySubscription>;
+
+const MAX_DEPTH: usize = 10;
+const MAX_COMPLEXITY: usize = 1_000;
+/// Persisted queries remembered per instance
+const PERSISTED_QUERY_CAPACITY: usize = 1_024;
+
+static SCHEMA: Lazy<AtlasSchema> = Lazy::new(|| {
+    Schema::build(QueryRoot, MutationRoot, EmptySubscription)
+        .extension(ApolloPersistedQueries::new(LruCacheStorage::new(PERSISTED_QUERY_CAPACITY)))
+        .limit_depth(MAX_DEPTH)
+        .limit_complexity(MAX_COMPLEXITY)
+        .finish()
+});
+
+/// The schema; per-request data (claims, loaders) is attached to each request
+pub fn schema() -> &'static AtlasSchema {
+    &SCHEMA
+}
+
+/// The SDL of the schema, for client code generation
+pub fn sdl() -> String {
+    SCHEMA.sdl()
+}
+
+/// GraphQL error with the client-safe message and status of the REST error body
+pub fn graphql_error(err: &AppError) -> async_graphql::Error {
+    crate::middleware::error_tracking::capture_app_error(err);
+    let (status, message) = err.client_error();
+    async_graphql::Error::new(message).extend_with(|_, extensions| {
+        extensions.set("status", status.as_u16());
+        extensions.set("code", status.canonical_reason().unwrap_or("Error").to_uppercase().replace(' ', "_"));
+    })
+}
+
+/// Convert service results for resolvers
+pub trait GqlResultExt<T> {
+    fn gql(self) -> async_graphql::Result<T>;
+}
+
+impl<T> GqlResultExt<T> for crate::middleware::error_handling::Result<T> {
+    fn gql(self) -> async_graphql::Result<T> {
+        self.map_err(|err| graphql_error(&err))
+    }
+}
+
+impl<T> GqlResultExt<T> for std::result::Result<T, std::sync::Arc<AppError>> {
+    fn gql(self) -> async_graphql::Result<T> {
+        self.map_err(|err| graphql_error(&err))
+    }
+}
+
+/// The authenticated caller
+pub fn viewer<'a>(ctx: &Context<'a>) -> async_graphql::Result<&'a Claims> {
+    ctx.data::<Claims>().map_err(|_| graphql_error(&AppError::Unauthorized))
+}
+
+/// Error of a field the caller may not read; the rest of the response is still returned
+pub fn forbidden(message: &str) -> async_graphql::Error {
+    graphql_error(&AppError::Forbidden(message.to_string()))
+}
@@ -0,0 +1,360 @@
+// GraphQL Root Types
+// Root fields wrap the same repositories and services as the REST handlers and apply
+// their checks: a caller sees their own inventory, inquiries and transactions they
+// take part in, and their own notifications.
+
+use async_graphql::dataloader::DataLoader;
+use async_graphql::{Context, Guard, InputObject, Object, Result};
+use chrono::NaiveDate;
+use rust_decimal::Decimal;
+use uuid::Uuid;
+use super::loaders::{InquiryLoader, InventoryLoader, PharmaceuticalLoader, UserLoader};
+use super::types::{
+    CatalogSearchResult, InquiryRole, InventoryItem, Inquiry, NotificationPage, Pharmaceutical, ProductDomain,
+    Transaction, User,
+};
+use super::{forbidden, viewer, GqlResultExt};
+use crate::{
+    config::{AppConfig, ReadConsistency},
+    models::{
+        alerts::GetNotificationsQuery,
+        federated_search::FederatedSearchRequest,
+        inventory::SearchInventoryRequest,
+        pharmaceutical::SearchPharmaceuticalRequest,
+    },
+    repositories::{InventoryRepository, MarketplaceRepository, PharmaceuticalRepository},
+    services::{
+        federated_search_service::FederatedSearchService, inventory_service::InventoryService,
+        notification_service::NotificationService, pharma_service::PharmaService,
+    },
+};
+
+/// Page size of list fields; the repositories cap it at 100 as well
+const MAX_PAGE_SIZE: i64 = 100;
+
+fn page_size(limit: i64) -> usize {
+    limit.clamp(1, MAX_PAGE_SIZE) as usize
+}
+
+fn app_config<'a>(ctx: &Context<'a>) -> Result<&'a AppConfig> {
+    ctx.data::<AppConfig>()
+}
+
+struct AdminGuard;
+
+impl Guard for AdminGuard {
+    async fn check(&self, ctx: &Context<'_>) -> Result<()> {
+        if viewer(ctx)?.is_admin() {
+            Ok(())
+        } else {
+            Err(forbidden("Admin access required"))
+        }
+    }
+}
+
+/// Filters of `pharmaceuticals` (see GET /api/pharmaceuticals/search)
+#[derive(InputObject, Default)]
+pub struct PharmaceuticalFilter {
+    pub query: Option<String>,
+    pub brand_name: Option<String>,
+    pub generic_name: Option<String>,
+    pub manufacturer: Option<String>,
+    pub category: Option<String>,
+    pub ndc_code: Option<String>,
+    pub product_domain: Option<ProductDomain>,
+    pub udi_di: Option<String>,
+    /// Allergen classes to exclude, e.g. `lactose-free`
+    pub free_of: Option<Vec<String>>,
+}
+
+/// Filters of `marketplaceSearch` (see GET /api/marketplace/search)
+#[derive(InputObject, Default)]
+pub struct MarketplaceFilter {
+    /// Free text over product, manufacturer and seller names
+    pub q: Option<String>,
+    pub pharmaceutical_id: Option<Uuid>,
+    pub brand_name: Option<String>,
+    pub generic_name: Option<String>,
+    pub manufacturer: Option<String>,
+    pub ndc_code: Option<String>,
+    pub expiry_before: Option<NaiveDate>,
+    pub expiry_after: Option<NaiveDate>,
+    pub min_quantity: Option<i32>,
+    pub max_quantity: Option<i32>,
+    pub status: Option<String>,
+    pub min_price: Option<Decimal>,
+    pub max_price: Option<Decimal>,
+    pub product_domain: Option<ProductDomain>,
+    /// Allergen classes to exclude, e.g. `lactose-free`
+    pub free_of: Option<Vec<String>>,
+    pub sort_by: Option<String>,
+    pub sort_order: Option<String>,
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// The authenticated user
+    async fn me(&self, ctx: &Context<'_>) -> Result<Option<User>> {
+        let viewer = viewer(ctx)?;
+        let loader = ctx.data::<DataLoader<UserLoader>>()?;
+        Ok(loader.load_one(viewer.user_id).await.gql()?.map(User))
+    }
+
+    /// Any user (admins only)
+    #[graphql(guard = "AdminGuard")]
+    async fn user(&self, ctx: &Context<'_>, id: Uuid) -> Result<Option<User>> {
+        let loader = ctx.data::<DataLoader<UserLoader>>()?;
+        Ok(loader.load_one(id).await.gql()?.map(User))
+    }
+
+    async fn pharmaceutical(&self, ctx: &Context<'_>, id: Uuid) -> Result<Option<Pharmaceutical>> {
+        let loader = ctx.data::<DataLoader<PharmaceuticalLoader>>()?;
+        Ok(loader.load_one(id).await.gql()?.map(Pharmaceutical))
+    }
+
+    /// Catalog products
+    #[graphql(complexity = "page_size(limit) * child_complexity")]
+    async fn pharmaceuticals(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<PharmaceuticalFilter>,
+        #[graphql(default = 50)] limit: i64,
+        #[graphql(default = 0)] offset: i64,
+    ) -> Result<Vec<Pharmaceutical>> {
+        let config = app_config(ctx)?;
+        let filter = filter.unwrap_or_default();
+        let request = SearchPharmaceuticalRequest {
+            query: filter.query,
+            brand_name: filter.brand_name,
+            generic_name: filter.generic_name,
+            manufacturer: filter.manufacturer,
+            category: filter.category,
+            ndc_code: filter.ndc_code,
+            product_domain: filter.product_domain.map(Into::into),
+            udi_di: filter.udi_di,
+            free_of: filter.free_of.map(|classes| classes.join(",")),
+            limit: Some(page_size(limit) as i64),
+            offset: Some(offset.max(0)),
+        };
+        let pharma_service = PharmaService::new(PharmaceuticalRepository::new(config.read_pool(ReadConsistency::Relaxed)));
+        let results = pharma_service.search_pharmaceuticals(request).await.gql()?;
+        Ok(results.into_iter().map(Pharmaceutical).collect())
+    }
+
+    /// Products across the regulatory catalogs (OpenFDA, EMA, ...), grouped by equivalence
+    #[graphql(complexity = "page_size(limit) * child_complexity")]
+    async fn catalog_search(
+        &self,
+        ctx: &Context<'_>,
+        query: Option<String>,
+        ingredient: Option<String>,
+        atc_code: Option<String>,
+        sources: Option<Vec<String>>,
+        #[graphql(default = 20)] limit: i64,
+        #[graphql(default = 0)] offset: i64,
+    ) -> Result<CatalogSearchResult> {
+        let config = app_config(ctx)?;
+        let request = FederatedSearchRequest {
+            query,
+            ingredient,
+            atc_code,
+            sources: sources.map(|sources| sources.join(",")),
+            limit: Some(page_size(limit) as i64),
+            offset: Some(offset.max(0)),
+        };
+        let service = FederatedSearchService::new(config.read_pool(ReadConsistency::Relaxed));
+        Ok(service.search(&request).await.gql()?.into())
+    }
+
+    /// The caller's own listings
+    #[graphql(complexity = "page_size(limit) * child_complexity")]
+    async fn my_inventory(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = 50)] limit: i64,
+        #[graphql(default = 0)] offset: i64,
+    ) -> Result<Vec<InventoryItem>> {
+        let viewer = viewer(ctx)?;
+        let config = app_config(ctx)?;
+        let repo = InventoryRepository::new(config.read_pool(ReadConsistency::ReadYourWrites(viewer.user_id)));
+        let inventories = repo.find_by_user(viewer.user_id, Some(limit), Some(offset.max(0))).await.gql()?;
+        Ok(inventories.into_iter().map(InventoryItem).collect())
+    }
+
+    /// One of the caller's own listings
+    async fn inventory_item(&self, ctx: &Context<'_>, id: Uuid) -> Result<Option<InventoryItem>> {
+        let viewer = viewer(ctx)?;
+        let loader = ctx.data::<DataLoader<InventoryLoader>>()?;
+        match loader.load_one(id).await.gql()? {
+            Some(inventory) if inventory.user_id != viewer.user_id => Err(forbidden("Access denied")),
+            inventory => Ok(inventory.map(InventoryItem)),
+        }
+    }
+
+    /// Listings of all sellers
+    #[graphql(complexity = "page_size(limit) * child_complexity")]
+    async fn marketplace_search(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<MarketplaceFilter>,
+        #[graphql(default = 50)] limit: i64,
+        #[graphql(default = 0)] offset: i64,
+    ) -> Result<Vec<InventoryItem>> {
+        let viewer = viewer(ctx)?;
+        let config = app_config(ctx)?;
+        let filter = filter.unwrap_or_default();
+        let request = SearchInventoryRequest {
+            q: filter.q,
+            pharmaceutical_id: filter.pharmaceutical_id,
+            brand_name: filter.brand_name,
+            generic_name: filter.generic_name,
+            manufacturer: filter.manufacturer,
+            ndc_code: filter.ndc_code,
+            expiry_before: filter.expiry_before,
+            expiry_after: filter.expiry_after,
+            min_quantity: filter.min_quantity,
+            max_quantity: filter.max_quantity,
+            status: filter.status,
+            min_price: filter.min_price,
+            max_price: filter.max_price,
+            product_domain: filter.product_domain.map(Into::into),
+            free_of: filter.free_of.map(|classes| classes.join(",")),
+            facets: None,
+            limit: Some(page_size(limit) as i64),
+            offset: Some(offset.max(0)),
+            sort_by: filter.sort_by,
+            sort_order: filter.sort_order,
+        };
+
+        // Sellers who just changed a listing search the primary until the replica has it
+        let read_pool = config.read_pool(ReadConsistency::ReadYourWrites(viewer.user_id));
+        let inventory_service = InventoryService::new(
+            InventoryRepository::new(read_pool.clone()),
+            PharmaceuticalRepository::new(read_pool),
+        );
+        let response = inventory_service
+            .search_marketplace(request, config.search_engine.as_ref())
+            .await
+            .gql()?;
+
+        // The results already carry their products; hand them to the loader
+        let (items, pharmaceuticals): (Vec<_>, Vec<_>) = response.results.into_iter().map(InventoryItem::from_listing).unzip();
+        ctx.data::<DataLoader<PharmaceuticalLoader>>()?
+            .feed_many(pharmaceuticals.into_iter().map(|pharma| (pharma.id, pharma)))
+            .await;
+        Ok(items)
+    }
+
+    /// Inquiries the caller sent (buyer) or received (seller)
+    #[graphql(complexity = "page_size(limit) * child_complexity")]
+    async fn inquiries(
+        &self,
+        ctx: &Context<'_>,
+        role: InquiryRole,
+        #[graphql(default = 50)] limit: i64,
+        #[graphql(default = 0)] offset: i64,
+    ) -> Result<Vec<Inquiry>> {
+        let viewer = viewer(ctx)?;
+        let repo = MarketplaceRepository::new(app_config(ctx)?.database_pool.clone());
+        let (limit, offset) = (Some(limit), Some(offset.max(0)));
+        let inquiries = match role {
+            InquiryRole::Buyer => repo.get_inquiries_for_buyer(viewer.user_id, limit, offset).await,
+            InquiryRole::Seller => repo.get_inquiries_for_seller(viewer.user_id, limit, offset).await,
+        }
+        .gql()?;
+        Ok(inquiries.into_iter().map(Inquiry).collect())
+    }
+
+    /// An inquiry the caller sent or received
+    async fn inquiry(&self, ctx: &Context<'_>, id: Uuid) -> Result<Option<Inquiry>> {
+        let viewer = viewer(ctx)?;
+        let repo = MarketplaceRepository::new(app_config(ctx)?.database_pool.clone());
+        if !repo.can_access_inquiry(id, viewer.user_id).await.gql()? {
+            return Err(forbidden("Access denied"));
+        }
+        let loader = ctx.data::<DataLoader<InquiryLoader>>()?;
+        Ok(loader.load_one(id).await.gql()?.map(Inquiry))
+    }
+
+    /// Transactions the caller is the buyer or seller of
+    #[graphql(complexity = "page_size(limit) * child_complexity")]
+    async fn transactions(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = 50)] limit: i64,
+        #[graphql(default = 0)] offset: i64,
+    ) -> Result<Vec<Transaction>> {
+        let viewer = viewer(ctx)?;
+        let repo = MarketplaceRepository::new(app_config(ctx)?.database_pool.clone());
+        let transactions = repo
+            .get_transactions_for_user(viewer.user_id, Some(limit), Some(offset.max(0)))
+            .await
+            .gql()?;
+        Ok(transactions.into_iter().map(Transaction).collect())
+    }
+
+    /// A transaction the caller is the buyer or seller of
+    async fn transaction(&self, ctx: &Context<'_>, id: Uuid) -> Result<Option<Transaction>> {
+        let viewer = viewer(ctx)?;
+        let repo = MarketplaceRepository::new(app_config(ctx)?.database_pool.clone());
+        if !repo.can_access_transaction(id, viewer.user_id).await.gql()? {
+            return Err(forbidden("Access denied"));
+        }
+        Ok(repo.find_transaction_by_id(id).await.gql()?.map(Transaction))
+    }
+
+    /// The caller's notifications, newest first
+    #[graphql(complexity = "page_size(limit) * child_complexity")]
+    async fn notifications(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = false)] unread_only: bool,
+        alert_type: Option<String>,
+        #[graphql(default = 50)] limit: i64,
+        #[graphql(default = 0)] offset: i64,
+    ) -> Result<NotificationPage> {
+        let viewer = viewer(ctx)?;
+        let service = NotificationService::new(app_config(ctx)?.database_pool.clone());
+        let query = GetNotificationsQuery {
+            limit: Some(limit),
+            offset: Some(offset.max(0)),
+            unread_only: Some(unread_only),
+            alert_type,
+        };
+        Ok(service.get_user_notifications(viewer.user_id, query).await.gql()?.into())
+    }
+}
+
+pub struct MutationRoot;
+
+#[Object]
+impl MutationRoot {
+    /// Mark one of the caller's notifications read (or unread)
+    async fn mark_notification_read(
+        &self,
+        ctx: &Context<'_>,
+        id: Uuid,
+        #[graphql(default = true)] is_read: bool,
+    ) -> Result<bool> {
+        let viewer = viewer(ctx)?;
+        let service = NotificationService::new(app_config(ctx)?.database_pool.clone());
+        service.mark_as_read(id, viewer.user_id, is_read).await.gql()?;
+        Ok(true)
+    }
+
+    /// Mark all of the caller's notifications read; returns how many changed
+    async fn mark_all_notifications_read(&self, ctx: &Context<'_>) -> Result<u64> {
+        let viewer = viewer(ctx)?;
+        let service = NotificationService::new(app_config(ctx)?.database_pool.clone());
+        service.mark_all_read(viewer.user_id).await.gql()
+    }
+
+    async fn dismiss_notification(&self, ctx: &Context<'_>, id: Uuid) -> Result<bool> {
+        let viewer = viewer(ctx)?;
+        let service = NotificationService::new(app_config(ctx)?.database_pool.clone());
+        service.dismiss_notification(id, viewer.user_id).await.gql()?;
+        Ok(true)
+    }
+}
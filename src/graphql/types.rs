@@ -0,0 +1,592 @@
+// GraphQL Types
+// Wrappers around the REST models. Related records resolve through the request's
+// DataLoaders; fields not every caller may read check the viewer themselves and
+// return a field error (the rest of the response is unaffected):
+// - contact details of a user: the user, admins and verified companies
+// - storage location of a listing: its seller and admins
+// - ERP order numbers of a transaction: the side that exported them, and admins
+
+use async_graphql::dataloader::DataLoader;
+use async_graphql::{Context, Enum, Json, Object, Result, SimpleObject};
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+use super::loaders::{CatalogEntryLoader, InquiryLoader, InventoryLoader, PharmaceuticalLoader, UserLoader};
+use super::{forbidden, viewer, GqlResultExt};
+use crate::middleware::Claims;
+use crate::models::{
+    alerts::{AlertNotificationResponse, NotificationSummary},
+    federated_search::{EquivalenceGroup, FederatedSearchResponse, FederatedSearchResult},
+    inventory::{Inventory, InventoryResponse},
+    marketplace,
+    openfda::OpenFdaCatalogEntry,
+    openfda_recall::extract_ndcs,
+    pharmaceutical::PharmaceuticalResponse,
+    user::UserResponse,
+};
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+#[graphql(remote = "crate::models::pharmaceutical::ProductDomain")]
+pub enum ProductDomain {
+    HumanDrug,
+    Veterinary,
+    MedicalDevice,
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq)]
+pub enum InquiryRole {
+    Buyer,
+    Seller,
+}
+
+async fn load_user(ctx: &Context<'_>, id: Uuid) -> Result<Option<User>> {
+    let loader = ctx.data::<DataLoader<UserLoader>>()?;
+    Ok(loader.load_one(id).await.gql()?.map(User))
+}
+
+async fn load_listing(ctx: &Context<'_>, id: Uuid) -> Result<Option<InventoryItem>> {
+    let loader = ctx.data::<DataLoader<InventoryLoader>>()?;
+    Ok(loader.load_one(id).await.gql()?.map(InventoryItem))
+}
+
+// ============================================================================
+// USERS
+// ============================================================================
+
+pub struct User(pub UserResponse);
+
+impl User {
+    fn contact<'a>(&self, ctx: &Context<'_>, value: &'a str) -> Result<&'a str> {
+        let viewer = viewer(ctx)?;
+        if viewer.user_id == self.0.id || viewer.is_admin() || viewer.is_verified {
+            Ok(value)
+        } else {
+            Err(forbidden("Contact details are visible to verified companies"))
+        }
+    }
+}
+
+#[Object]
+impl User {
+    async fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    async fn company_name(&self) -> &str {
+        &self.0.company_name
+    }
+
+    async fn is_verified(&self) -> bool {
+        self.0.is_verified
+    }
+
+    async fn role(&self) -> &str {
+        self.0.role.as_str()
+    }
+
+    async fn created_at(&self) -> DateTime<Utc> {
+        self.0.created_at
+    }
+
+    async fn email(&self, ctx: &Context<'_>) -> Result<&str> {
+        self.contact(ctx, &self.0.email)
+    }
+
+    async fn contact_person(&self, ctx: &Context<'_>) -> Result<&str> {
+        self.contact(ctx, &self.0.contact_person)
+    }
+
+    async fn phone(&self, ctx: &Context<'_>) -> Result<Option<&str>> {
+        self.0.phone.as_deref().map(|phone| self.contact(ctx, phone)).transpose()
+    }
+
+    async fn address(&self, ctx: &Context<'_>) -> Result<Option<&str>> {
+        self.0.address.as_deref().map(|address| self.contact(ctx, address)).transpose()
+    }
+
+    async fn license_number(&self, ctx: &Context<'_>) -> Result<Option<&str>> {
+        self.0.license_number.as_deref().map(|license| self.contact(ctx, license)).transpose()
+    }
+}
+
+// ============================================================================
+// CATALOG
+// ============================================================================
+
+pub struct Pharmaceutical(pub PharmaceuticalResponse);
+
+#[Object]
+impl Pharmaceutical {
+    async fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    async fn brand_name(&self) -> &str {
+        &self.0.brand_name
+    }
+
+    async fn generic_name(&self) -> &str {
+        &self.0.generic_name
+    }
+
+    async fn ndc_code(&self) -> Option<&str> {
+        self.0.ndc_code.as_deref()
+    }
+
+    async fn manufacturer(&self) -> &str {
+        &self.0.manufacturer
+    }
+
+    async fn category(&self) -> Option<&str> {
+        self.0.category.as_deref()
+    }
+
+    async fn description(&self) -> Option<&str> {
+        self.0.description.as_deref()
+    }
+
+    async fn strength(&self) -> Option<&str> {
+        self.0.strength.as_deref()
+    }
+
+    async fn dosage_form(&self) -> Option<&str> {
+        self.0.dosage_form.as_deref()
+    }
+
+    async fn storage_requirements(&self) -> Option<&str> {
+        self.0.storage_requirements.as_deref()
+    }
+
+    /// human_drug, veterinary or medical_device
+    async fn product_domain(&self) -> &str {
+        &self.0.product_domain
+    }
+
+    async fn udi_di(&self) -> Option<&str> {
+        self.0.udi_di.as_deref()
+    }
+
+    async fn device_class(&self) -> Option<&str> {
+        self.0.device_class.as_deref()
+    }
+
+    async fn target_species(&self) -> Option<&Vec<String>> {
+        self.0.target_species.as_ref()
+    }
+
+    async fn created_at(&self) -> DateTime<Utc> {
+        self.0.created_at
+    }
+
+    /// The OpenFDA listing of the product's NDC, when synced
+    async fn catalog_entry(&self, ctx: &Context<'_>) -> Result<Option<CatalogEntry>> {
+        let Some((product_ndc, _)) = self.0.ndc_code.as_deref().and_then(|ndc| extract_ndcs(ndc).into_iter().next()) else {
+            return Ok(None);
+        };
+        let loader = ctx.data::<DataLoader<CatalogEntryLoader>>()?;
+        Ok(loader.load_one(product_ndc).await.gql()?.map(CatalogEntry))
+    }
+}
+
+pub struct CatalogEntry(pub OpenFdaCatalogEntry);
+
+#[Object]
+impl CatalogEntry {
+    async fn product_ndc(&self) -> &str {
+        &self.0.product_ndc
+    }
+
+    async fn brand_name(&self) -> &str {
+        &self.0.brand_name
+    }
+
+    async fn generic_name(&self) -> &str {
+        &self.0.generic_name
+    }
+
+    async fn labeler_name(&self) -> &str {
+        &self.0.labeler_name
+    }
+
+    async fn dosage_form(&self) -> Option<&str> {
+        self.0.dosage_form.as_deref()
+    }
+
+    async fn route(&self) -> Option<&Vec<String>> {
+        self.0.route.as_ref()
+    }
+
+    async fn strength(&self) -> Option<&str> {
+        self.0.strength.as_deref()
+    }
+
+    async fn active_ingredients(&self) -> Option<Json<serde_json::Value>> {
+        self.0.active_ingredients.clone().map(Json)
+    }
+
+    async fn product_type(&self) -> Option<&str> {
+        self.0.product_type.as_deref()
+    }
+
+    async fn marketing_category(&self) -> Option<&str> {
+        self.0.marketing_category.as_deref()
+    }
+
+    async fn pharm_class(&self) -> Option<&Vec<String>> {
+        self.0.pharm_class.as_ref()
+    }
+
+    async fn dea_schedule(&self) -> Option<&str> {
+        self.0.dea_schedule.as_deref()
+    }
+
+    async fn marketing_start_date(&self) -> Option<NaiveDate> {
+        self.0.marketing_start_date
+    }
+
+    async fn listing_expiration_date(&self) -> Option<NaiveDate> {
+        self.0.listing_expiration_date
+    }
+
+    async fn last_synced_at(&self) -> DateTime<Utc> {
+        self.0.last_synced_at
+    }
+}
+
+/// A product from any regulator catalog, in a common shape
+#[derive(SimpleObject)]
+pub struct CatalogProduct {
+    pub source: String,
+    pub source_id: String,
+    pub country: String,
+    pub product_name: String,
+    pub active_ingredients: Vec<String>,
+    pub strength: Option<String>,
+    pub dosage_form: Option<String>,
+    pub manufacturer: Option<String>,
+    pub authorization_status: Option<String>,
+    pub atc_code: Option<String>,
+    pub rxcuis: Vec<String>,
+    pub equivalence_key: Option<String>,
+}
+
+impl From<FederatedSearchResult> for CatalogProduct {
+    fn from(result: FederatedSearchResult) -> Self {
+        Self {
+            source: result.source,
+            source_id: result.source_id,
+            country: result.country,
+            product_name: result.product_name,
+            active_ingredients: result.active_ingredients,
+            strength: result.strength,
+            dosage_form: result.dosage_form,
+            manufacturer: result.manufacturer,
+            authorization_status: result.authorization_status,
+            atc_code: result.atc_code,
+            rxcuis: result.rxcuis,
+            equivalence_key: result.equivalence_key,
+        }
+    }
+}
+
+/// Products on the page that are the same substance(s) in different catalogs
+#[derive(SimpleObject)]
+pub struct CatalogEquivalenceGroup {
+    pub equivalence_key: String,
+    pub atc_code: Option<String>,
+    pub sources: Vec<String>,
+    pub source_ids: Vec<String>,
+}
+
+impl From<EquivalenceGroup> for CatalogEquivalenceGroup {
+    fn from(group: EquivalenceGroup) -> Self {
+        Self {
+            equivalence_key: group.equivalence_key,
+            atc_code: group.atc_code,
+            sources: group.sources,
+            source_ids: group.source_ids,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct CatalogSearchResult {
+    pub results: Vec<CatalogProduct>,
+    pub groups: Vec<CatalogEquivalenceGroup>,
+    /// Catalogs searched
+    pub sources: Vec<String>,
+    pub has_more: bool,
+}
+
+impl From<FederatedSearchResponse> for CatalogSearchResult {
+    fn from(response: FederatedSearchResponse) -> Self {
+        Self {
+            results: response.results.into_iter().map(Into::into).collect(),
+            groups: response.groups.into_iter().map(Into::into).collect(),
+            sources: response.sources,
+            has_more: response.has_more,
+        }
+    }
+}
+
+// ============================================================================
+// INVENTORY
+// ============================================================================
+
+pub struct InventoryItem(pub Inventory);
+
+impl InventoryItem {
+    /// A marketplace search result, and its product for priming the loader
+    pub fn from_listing(listing: InventoryResponse) -> (Self, PharmaceuticalResponse) {
+        let inventory = Inventory {
+            id: listing.id,
+            user_id: listing.seller.id,
+            pharmaceutical_id: listing.pharmaceutical.id,
+            batch_number: listing.batch_number,
+            quantity: listing.quantity,
+            expiry_date: listing.expiry_date,
+            unit_price: listing.unit_price,
+            storage_location: listing.storage_location,
+            status: listing.status,
+            created_at: listing.created_at,
+            updated_at: listing.updated_at,
+        };
+        (Self(inventory), listing.pharmaceutical)
+    }
+}
+
+#[Object]
+impl InventoryItem {
+    async fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    async fn batch_number(&self) -> &str {
+        &self.0.batch_number
+    }
+
+    async fn quantity(&self) -> i32 {
+        self.0.quantity
+    }
+
+    async fn expiry_date(&self) -> NaiveDate {
+        self.0.expiry_date
+    }
+
+    async fn days_to_expiry(&self) -> i64 {
+        self.0.expiry_date.signed_duration_since(Utc::now().date_naive()).num_days()
+    }
+
+    async fn unit_price(&self) -> Option<Decimal> {
+        self.0.unit_price
+    }
+
+    async fn status(&self) -> &str {
+        &self.0.status
+    }
+
+    async fn created_at(&self) -> DateTime<Utc> {
+        self.0.created_at
+    }
+
+    async fn updated_at(&self) -> DateTime<Utc> {
+        self.0.updated_at
+    }
+
+    /// Seller and admins only
+    async fn storage_location(&self, ctx: &Context<'_>) -> Result<Option<&str>> {
+        let viewer = viewer(ctx)?;
+        if viewer.user_id != self.0.user_id && !viewer.is_admin() {
+            return Err(forbidden("Storage locations are visible to the seller"));
+        }
+        Ok(self.0.storage_location.as_deref())
+    }
+
+    async fn pharmaceutical(&self, ctx: &Context<'_>) -> Result<Option<Pharmaceutical>> {
+        let loader = ctx.data::<DataLoader<PharmaceuticalLoader>>()?;
+        Ok(loader.load_one(self.0.pharmaceutical_id).await.gql()?.map(Pharmaceutical))
+    }
+
+    async fn seller(&self, ctx: &Context<'_>) -> Result<Option<User>> {
+        load_user(ctx, self.0.user_id).await
+    }
+}
+
+// ============================================================================
+// MARKETPLACE
+// ============================================================================
+
+pub struct Inquiry(pub marketplace::Inquiry);
+
+#[Object]
+impl Inquiry {
+    async fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    async fn quantity_requested(&self) -> i32 {
+        self.0.quantity_requested
+    }
+
+    async fn message(&self) -> Option<&str> {
+        self.0.message.as_deref()
+    }
+
+    async fn status(&self) -> &str {
+        &self.0.status
+    }
+
+    async fn created_at(&self) -> DateTime<Utc> {
+        self.0.created_at
+    }
+
+    async fn updated_at(&self) -> DateTime<Utc> {
+        self.0.updated_at
+    }
+
+    async fn listing(&self, ctx: &Context<'_>) -> Result<Option<InventoryItem>> {
+        load_listing(ctx, self.0.inventory_id).await
+    }
+
+    async fn buyer(&self, ctx: &Context<'_>) -> Result<Option<User>> {
+        load_user(ctx, self.0.buyer_id).await
+    }
+
+    async fn seller(&self, ctx: &Context<'_>) -> Result<Option<User>> {
+        match load_listing(ctx, self.0.inventory_id).await? {
+            Some(listing) => load_user(ctx, listing.0.user_id).await,
+            None => Ok(None),
+        }
+    }
+}
+
+pub struct Transaction(pub marketplace::Transaction);
+
+impl Transaction {
+    fn erp_order_number<'a>(&self, viewer: &Claims, party: Uuid, number: &'a Option<String>) -> Result<Option<&'a str>> {
+        if viewer.user_id != party && !viewer.is_admin() {
+            return Err(forbidden("ERP order numbers are visible to the party that exported them"));
+        }
+        Ok(number.as_deref())
+    }
+}
+
+#[Object]
+impl Transaction {
+    async fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    async fn quantity(&self) -> i32 {
+        self.0.quantity
+    }
+
+    async fn unit_price(&self) -> Decimal {
+        self.0.unit_price
+    }
+
+    async fn total_price(&self) -> Decimal {
+        self.0.total_price
+    }
+
+    async fn transaction_date(&self) -> DateTime<Utc> {
+        self.0.transaction_date
+    }
+
+    async fn status(&self) -> &str {
+        &self.0.status
+    }
+
+    /// Seller and admins only
+    async fn erp_sales_order_number(&self, ctx: &Context<'_>) -> Result<Option<&str>> {
+        self.erp_order_number(viewer(ctx)?, self.0.seller_id, &self.0.erp_sales_order_number)
+    }
+
+    /// Buyer and admins only
+    async fn erp_purchase_order_number(&self, ctx: &Context<'_>) -> Result<Option<&str>> {
+        self.erp_order_number(viewer(ctx)?, self.0.buyer_id, &self.0.erp_purchase_order_number)
+    }
+
+    async fn inquiry(&self, ctx: &Context<'_>) -> Result<Option<Inquiry>> {
+        let loader = ctx.data::<DataLoader<InquiryLoader>>()?;
+        Ok(loader.load_one(self.0.inquiry_id).await.gql()?.map(Inquiry))
+    }
+
+    async fn buyer(&self, ctx: &Context<'_>) -> Result<Option<User>> {
+        load_user(ctx, self.0.buyer_id).await
+    }
+
+    async fn seller(&self, ctx: &Context<'_>) -> Result<Option<User>> {
+        load_user(ctx, self.0.seller_id).await
+    }
+}
+
+// ============================================================================
+// NOTIFICATIONS
+// ============================================================================
+
+pub struct Notification(pub AlertNotificationResponse);
+
+#[Object]
+impl Notification {
+    async fn id(&self) -> Uuid {
+        self.0.id
+    }
+
+    async fn alert_type(&self) -> &str {
+        &self.0.alert_type
+    }
+
+    async fn severity(&self) -> &str {
+        &self.0.severity
+    }
+
+    async fn title(&self) -> &str {
+        &self.0.title
+    }
+
+    async fn message(&self) -> &str {
+        &self.0.message
+    }
+
+    async fn metadata(&self) -> Option<Json<serde_json::Value>> {
+        self.0.metadata.clone().map(Json)
+    }
+
+    async fn action_url(&self) -> Option<&str> {
+        self.0.action_url.as_deref()
+    }
+
+    async fn is_read(&self) -> bool {
+        self.0.is_read
+    }
+
+    async fn created_at(&self) -> DateTime<Utc> {
+        self.0.created_at
+    }
+
+    /// The listing the notification is about, if any
+    async fn listing(&self, ctx: &Context<'_>) -> Result<Option<InventoryItem>> {
+        match self.0.inventory_id {
+            Some(inventory_id) => load_listing(ctx, inventory_id).await,
+            None => Ok(None),
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct NotificationPage {
+    pub total_unread: i64,
+    pub total_notifications: i64,
+    pub nodes: Vec<Notification>,
+}
+
+impl From<NotificationSummary> for NotificationPage {
+    fn from(summary: NotificationSummary) -> Self {
+        Self {
+            total_unread: summary.total_unread,
+            total_notifications: summary.total_notifications,
+            nodes: summary.notifications.into_iter().map(Notification).collect(),
+        }
+    }
+}
@@ -0,0 +1,349 @@
+// Accounting Connector API Handlers
+// QuickBooks Online / Xero connections under /api/erp/accounting-connections (OAuth2
+// authorization, export settings) and the invoices pushed for marketplace sales
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+    Extension,
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::middleware::auth::Claims;
+use crate::middleware::error_handling::{AppError, Result};
+use crate::models::accounting::{
+    AccountingConnectionResponse, CompleteAccountingAuthorizationRequest, CreateAccountingConnectionRequest,
+    UpdateAccountingSettingsRequest,
+};
+use crate::services::accounting::AccountingService;
+use crate::services::comprehensive_audit_service::{
+    ActionResult, AuditLogEntry, ComprehensiveAuditService, EventCategory, Severity,
+};
+
+fn accounting_service(pool: PgPool) -> Result<AccountingService> {
+    AccountingService::new(pool)
+}
+
+async fn audit(
+    pool: PgPool,
+    user_id: Uuid,
+    event_type: &str,
+    action: &str,
+    connection_id: Uuid,
+    connection_name: &str,
+    event_data: serde_json::Value,
+) {
+    ComprehensiveAuditService::new(pool)
+        .log(AuditLogEntry {
+            event_type: event_type.to_string(),
+            event_category: EventCategory::DataModification,
+            severity: Severity::Info,
+            actor_user_id: Some(user_id),
+            actor_type: "user".to_string(),
+            resource_type: Some("accounting_connection".to_string()),
+            resource_id: Some(connection_id.to_string()),
+            resource_name: Some(connection_name.to_string()),
+            action: action.to_string(),
+            action_result: ActionResult::Success,
+            event_data,
+            ..Default::default()
+        })
+        .await
+        .ok();
+}
+
+// ============================================================================
+// Connections
+// ============================================================================
+
+/// GET /api/erp/accounting-connections
+#[utoipa::path(
+    get,
+    path = "/api/erp/accounting-connections",
+    tag = "accounting",
+    responses(
+        (status = 200, description = "Accounting connections", body = Vec<AccountingConnectionResponse>),
+    )
+)]
+pub async fn list_accounting_connections(
+    State(pool): State<PgPool>,
+    Extension(claims): Extension<Claims>,
+) -> Result<impl IntoResponse> {
+    let connections = accounting_service(pool)?.list_connections(claims.user_id).await?;
+
+    Ok(Json(
+        connections.iter().map(AccountingConnectionResponse::from).collect::<Vec<_>>(),
+    ))
+}
+
+/// GET /api/erp/accounting-connections/:id
+#[utoipa::path(
+    get,
+    path = "/api/erp/accounting-connections/{id}",
+    tag = "accounting",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "The connection", body = AccountingConnectionResponse),
+    )
+)]
+pub async fn get_accounting_connection(
+    State(pool): State<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(connection_id): Path<Uuid>,
+) -> Result<impl IntoResponse> {
+    let connection = accounting_service(pool)?.get_connection(connection_id, claims.user_id).await?;
+
+    Ok(Json(AccountingConnectionResponse::from(&connection)))
+}
+
+/// Register the user's QuickBooks / Xero OAuth2 app; the response carries the URL where
+/// the user grants access
+/// POST /api/erp/accounting-connections
+#[utoipa::path(
+    post,
+    path = "/api/erp/accounting-connections",
+    tag = "accounting",
+    request_body = CreateAccountingConnectionRequest,
+    responses(
+        (status = 201, description = "Connection created; authorize it at the returned URL", body = crate::models::accounting::AccountingAuthorizationResponse),
+    )
+)]
+pub async fn create_accounting_connection(
+    State(pool): State<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<CreateAccountingConnectionRequest>,
+) -> Result<impl IntoResponse> {
+    request.validate()?;
+
+    let provider = request.provider;
+    let response = accounting_service(pool.clone())?
+        .create_connection(claims.user_id, request)
+        .await?;
+
+    audit(
+        pool,
+        claims.user_id,
+        "accounting_connection_created",
+        "create",
+        response.connection.id,
+        &response.connection.connection_name,
+        serde_json::json!({ "provider": provider.as_str() }),
+    )
+    .await;
+
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+/// Start (or restart) authorization, e.g. after the refresh token was revoked
+/// POST /api/erp/accounting-connections/:id/authorize
+#[utoipa::path(
+    post,
+    path = "/api/erp/accounting-connections/{id}/authorize",
+    tag = "accounting",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Authorization URL", body = crate::models::accounting::AccountingAuthorizationResponse),
+    )
+)]
+pub async fn start_accounting_authorization(
+    State(pool): State<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(connection_id): Path<Uuid>,
+) -> Result<impl IntoResponse> {
+    let service = accounting_service(pool)?;
+    let connection = service.get_connection(connection_id, claims.user_id).await?;
+
+    Ok(Json(service.start_authorization(&connection).await?))
+}
+
+/// Finish authorization with the code and state the provider redirected back with
+/// POST /api/erp/accounting-connections/:id/callback
+#[utoipa::path(
+    post,
+    path = "/api/erp/accounting-connections/{id}/callback",
+    tag = "accounting",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    request_body = CompleteAccountingAuthorizationRequest,
+    responses(
+        (status = 200, description = "The authorized connection", body = AccountingConnectionResponse),
+    )
+)]
+pub async fn complete_accounting_authorization(
+    State(pool): State<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(connection_id): Path<Uuid>,
+    Json(request): Json<CompleteAccountingAuthorizationRequest>,
+) -> Result<impl IntoResponse> {
+    let service = accounting_service(pool.clone())?;
+    let connection = service.get_connection(connection_id, claims.user_id).await?;
+    let connection = service.complete_authorization(&connection, &request).await?;
+
+    audit(
+        pool,
+        claims.user_id,
+        "accounting_connection_authorized",
+        "update",
+        connection.id,
+        &connection.connection_name,
+        serde_json::json!({ "provider": connection.provider, "tenant_id": connection.tenant_id }),
+    )
+    .await;
+
+    Ok(Json(AccountingConnectionResponse::from(&connection)))
+}
+
+/// PUT /api/erp/accounting-connections/:id/settings
+#[utoipa::path(
+    put,
+    path = "/api/erp/accounting-connections/{id}/settings",
+    tag = "accounting",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    request_body = UpdateAccountingSettingsRequest,
+    responses(
+        (status = 200, description = "The connection", body = AccountingConnectionResponse),
+    )
+)]
+pub async fn update_accounting_settings(
+    State(pool): State<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(connection_id): Path<Uuid>,
+    Json(request): Json<UpdateAccountingSettingsRequest>,
+) -> Result<impl IntoResponse> {
+    request.validate()?;
+
+    let service = accounting_service(pool.clone())?;
+    let connection = service.get_connection(connection_id, claims.user_id).await?;
+    let updated = service.update_settings(&connection, &request).await?;
+
+    audit(
+        pool,
+        claims.user_id,
+        "accounting_settings_updated",
+        "update",
+        updated.id,
+        &updated.connection_name,
+        serde_json::json!({
+            "export_invoices": updated.export_invoices,
+            "record_payments": updated.record_payments,
+            "customer_ref": updated.customer_ref,
+            "item_ref": updated.item_ref,
+            "payment_account_ref": updated.payment_account_ref,
+        }),
+    )
+    .await;
+
+    Ok(Json(AccountingConnectionResponse::from(&updated)))
+}
+
+/// DELETE /api/erp/accounting-connections/:id
+#[utoipa::path(
+    delete,
+    path = "/api/erp/accounting-connections/{id}",
+    tag = "accounting",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 204, description = "Connection deleted"),
+    )
+)]
+pub async fn delete_accounting_connection(
+    State(pool): State<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(connection_id): Path<Uuid>,
+) -> Result<impl IntoResponse> {
+    let service = accounting_service(pool.clone())?;
+    let connection = service.get_connection(connection_id, claims.user_id).await?;
+
+    if !service.delete_connection(connection_id, claims.user_id).await? {
+        return Err(AppError::NotFound("Accounting connection not found".to_string()));
+    }
+
+    audit(
+        pool,
+        claims.user_id,
+        "accounting_connection_deleted",
+        "delete",
+        connection.id,
+        &connection.connection_name,
+        serde_json::json!({ "provider": connection.provider }),
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ============================================================================
+// Invoice Exports
+// ============================================================================
+
+/// Invoices pushed for a transaction to the caller's accounting connections
+/// GET /api/erp/transactions/:id/invoices
+#[utoipa::path(
+    get,
+    path = "/api/erp/transactions/{id}/invoices",
+    tag = "accounting",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Invoices exported for the transaction", body = Vec<crate::models::accounting::AccountingExport>),
+    )
+)]
+pub async fn get_transaction_invoices(
+    State(pool): State<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(transaction_id): Path<Uuid>,
+) -> Result<impl IntoResponse> {
+    let exports = accounting_service(pool)?
+        .get_exports(transaction_id, claims.user_id)
+        .await?;
+
+    Ok(Json(exports))
+}
+
+/// Retry failed or missing invoices of a completed sale (seller only)
+/// POST /api/erp/transactions/:id/invoices/retry
+#[utoipa::path(
+    post,
+    path = "/api/erp/transactions/{id}/invoices/retry",
+    tag = "accounting",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Invoices exported for the transaction", body = Vec<crate::models::accounting::AccountingExport>),
+    )
+)]
+pub async fn retry_transaction_invoices(
+    State(pool): State<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(transaction_id): Path<Uuid>,
+) -> Result<impl IntoResponse> {
+    let seller_id: Uuid = sqlx::query_scalar("SELECT seller_id FROM transactions WHERE id = $1")
+        .bind(transaction_id)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Transaction not found".to_string()))?;
+
+    if seller_id != claims.user_id {
+        return Err(AppError::Forbidden("Only the seller invoices a transaction".to_string()));
+    }
+
+    let exports = accounting_service(pool)?
+        .export_transaction(transaction_id)
+        .await?;
+
+    Ok(Json(exports))
+}
@@ -18,16 +18,32 @@ use axum::{
     response::{IntoResponse, Response},
     Json,
 };
+use std::sync::Arc;
 use uuid::Uuid;
-use crate::config::AppConfig;
-use crate::middleware::{Claims, error_handling::{Result, AppError}};
+use crate::config::{AppConfig, ReadConsistency};
+use crate::middleware::{Claims, error_handling::{Result, AppError}, route_policy::{route_policy_report, RoutePolicyReport}};
 use crate::repositories::UserRepository;
 use crate::services::{
     AdminService,
+    AiResponseCacheService,
     admin_service::*,
+    comprehensive_audit_service::{ActionResult, AuditLogEntry, EventCategory, Severity},
     ComprehensiveAuditService,
+    HealthService,
+    LegalHoldService,
+    QueryCache,
+    QueryCacheKind,
+    search_engine::SearchIndexService,
+    TokenBlacklistService,
+    UserSuspensionService,
+    VerificationService,
 };
+use crate::models::health::HealthStatus;
+use crate::models::search_engine::{ReindexRequest, ReindexResponse, SearchEngineStatus, SearchIndex};
+use crate::models::verification::{BulkVerificationRequest, BulkVerificationResult};
+use crate::models::user_suspension::*;
 use crate::{require_admin, require_superadmin};
+use utoipa::IntoParams;
 
 // ============================================================================
 // USER MANAGEMENT ENDPOINTS
@@ -43,6 +59,18 @@ use crate::{require_admin, require_superadmin};
 /// - search: string (searches company_name)
 ///
 /// Requires: admin or superadmin role
+#[utoipa::path(
+    get,
+    path = "/api/admin/users",
+    tag = "admin",
+    summary = "List all users with filters",
+    params(
+        ListUsersQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = ListUsersResponse),
+    )
+)]
 pub async fn list_users(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -73,6 +101,18 @@ pub async fn list_users(
 /// - id: UUID
 ///
 /// Requires: admin or superadmin role
+#[utoipa::path(
+    get,
+    path = "/api/admin/users/{id}",
+    tag = "admin",
+    summary = "Get single user details",
+    params(
+        ("id" = String, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = crate::models::user::UserResponse),
+    )
+)]
 pub async fn get_user(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -115,6 +155,19 @@ pub async fn get_user(
 /// ```
 ///
 /// Requires: admin or superadmin role
+#[utoipa::path(
+    post,
+    path = "/api/admin/users/{id}/verify",
+    tag = "admin",
+    summary = "Verify or unverify a user",
+    params(
+        ("id" = String, Path),
+    ),
+    request_body = VerifyUserRequest,
+    responses(
+        (status = 200, description = "Success", body = crate::models::user::UserResponse),
+    )
+)]
 pub async fn verify_user(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -159,6 +212,19 @@ pub async fn verify_user(
 /// ```
 ///
 /// Requires: superadmin role ONLY (enforced by superadmin_middleware)
+#[utoipa::path(
+    put,
+    path = "/api/admin/users/{id}/role",
+    tag = "admin",
+    summary = "Change user role",
+    params(
+        ("id" = String, Path),
+    ),
+    request_body = ChangeUserRoleRequest,
+    responses(
+        (status = 200, description = "Success", body = crate::models::user::UserResponse),
+    )
+)]
 pub async fn change_user_role(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -199,6 +265,18 @@ pub async fn change_user_role(
 /// - id: UUID
 ///
 /// Requires: superadmin role ONLY (enforced by superadmin_middleware)
+#[utoipa::path(
+    delete,
+    path = "/api/admin/users/{id}",
+    tag = "admin",
+    summary = "Delete user",
+    params(
+        ("id" = String, Path),
+    ),
+    responses(
+        (status = 204, description = "No content"),
+    )
+)]
 pub async fn delete_user(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -220,6 +298,11 @@ pub async fn delete_user(
         return Err(AppError::BadRequest("Cannot delete your own account".to_string()));
     }
 
+    // Held users (or parties to held transactions) cannot be deleted
+    LegalHoldService::new(config.database_pool.clone())
+        .ensure_user_deletable(user_id, claims.user_id, "delete_user")
+        .await?;
+
     // Create admin service
     let user_repo = UserRepository::new(config.database_pool.clone(), &config.encryption_key)?;
     let audit_service = ComprehensiveAuditService::new(config.database_pool.clone());
@@ -236,15 +319,329 @@ pub async fn delete_user(
     Ok(StatusCode::NO_CONTENT)
 }
 
+// ============================================================================
+// SUSPENSION & LISTING TAKEDOWN ENDPOINTS
+// ============================================================================
+
+/// POST /api/admin/users/:id/suspend - Suspend a user
+///
+/// Revokes the user's sessions immediately, refuses sign-in and hides their
+/// listings until reinstated (or until the duration ends).
+///
+/// Request body:
+/// ```json
+/// {
+///   "reason": "Counterfeit listings under investigation",
+///   "duration_hours": 72
+/// }
+/// ```
+///
+/// Omit `duration_hours` to suspend indefinitely. Admin accounts can only be
+/// suspended by a superadmin; superadmins can't be suspended.
+///
+/// Requires: admin or superadmin role
+#[utoipa::path(
+    post,
+    path = "/api/admin/users/{id}/suspend",
+    tag = "admin",
+    summary = "Suspend a user",
+    params(
+        ("id" = String, Path),
+    ),
+    request_body = SuspendUserRequest,
+    responses(
+        (status = 200, description = "Success", body = UserSuspension),
+    )
+)]
+pub async fn suspend_user(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Extension(blacklist): Extension<Arc<TokenBlacklistService>>,
+    axum::extract::ConnectInfo(addr): axum::extract::ConnectInfo<std::net::SocketAddr>,
+    Path(user_id): Path<String>,
+    Json(request): Json<SuspendUserRequest>,
+) -> Result<Json<UserSuspension>> {
+    require_admin!(claims);
+
+    // Parse user ID
+    let user_id = Uuid::parse_str(&user_id)
+        .map_err(|_| AppError::BadRequest("Invalid user ID format".to_string()))?;
+
+    // Prevent self-suspension
+    if user_id == claims.user_id {
+        return Err(AppError::BadRequest("Cannot suspend your own account".to_string()));
+    }
+
+    let (reason, ends_at) = request.validate(chrono::Utc::now()).map_err(AppError::BadRequest)?;
+
+    let user_repo = UserRepository::new(config.database_pool.clone(), &config.encryption_key)?;
+    let target = user_repo
+        .find_by_id(user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+    if target.role.is_superadmin() || (target.role.is_admin() && !claims.is_superadmin()) {
+        return Err(AppError::Forbidden("Insufficient privileges to suspend this account".to_string()));
+    }
+
+    let suspension = UserSuspensionService::new(config.database_pool.clone())
+        .suspend(user_id, &reason, ends_at, claims.user_id)
+        .await?;
+
+    // 🔒 SECURITY: Reject the user's tokens on this instance right away; other
+    // instances apply the revocation when its NOTIFY reaches their listener
+    blacklist.revoke_sessions_before(user_id, suspension.suspended_at.timestamp(), "suspended");
+
+    ComprehensiveAuditService::new(config.database_pool.clone()).log(AuditLogEntry {
+        event_type: "admin_user_suspended".to_string(),
+        event_category: EventCategory::Admin,
+        severity: Severity::Warning,
+        actor_user_id: Some(claims.user_id),
+        actor_type: "user".to_string(),
+        resource_type: Some("user".to_string()),
+        resource_id: Some(user_id.to_string()),
+        action: "suspend_user".to_string(),
+        action_result: ActionResult::Success,
+        event_data: serde_json::json!({
+            "suspension_id": suspension.id,
+            "reason": reason,
+            "ends_at": ends_at,
+        }),
+        ip_address: Some(addr.ip()),
+        compliance_tags: vec!["admin".to_string(), "account_suspension".to_string()],
+        ..Default::default()
+    }).await?;
+
+    Ok(Json(suspension))
+}
+
+/// POST /api/admin/users/:id/reinstate - Lift a user's suspension
+///
+/// Request body (optional notes):
+/// ```json
+/// {
+///   "notes": "Investigation closed"
+/// }
+/// ```
+///
+/// Requires: admin or superadmin role
+#[utoipa::path(
+    post,
+    path = "/api/admin/users/{id}/reinstate",
+    tag = "admin",
+    summary = "Lift a user's suspension",
+    params(
+        ("id" = String, Path),
+    ),
+    request_body = Option<ReinstateUserRequest>,
+    responses(
+        (status = 200, description = "Success", body = UserSuspension),
+    )
+)]
+pub async fn reinstate_user(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    axum::extract::ConnectInfo(addr): axum::extract::ConnectInfo<std::net::SocketAddr>,
+    Path(user_id): Path<String>,
+    request: Option<Json<ReinstateUserRequest>>,
+) -> Result<Json<UserSuspension>> {
+    require_admin!(claims);
+
+    // Parse user ID
+    let user_id = Uuid::parse_str(&user_id)
+        .map_err(|_| AppError::BadRequest("Invalid user ID format".to_string()))?;
+
+    let Json(request) = request.unwrap_or_default();
+    let suspension = UserSuspensionService::new(config.database_pool.clone())
+        .reinstate(user_id, Some(claims.user_id), request.notes.as_deref())
+        .await?;
+
+    ComprehensiveAuditService::new(config.database_pool.clone()).log(AuditLogEntry {
+        event_type: "admin_user_reinstated".to_string(),
+        event_category: EventCategory::Admin,
+        severity: Severity::Info,
+        actor_user_id: Some(claims.user_id),
+        actor_type: "user".to_string(),
+        resource_type: Some("user".to_string()),
+        resource_id: Some(user_id.to_string()),
+        action: "reinstate_user".to_string(),
+        action_result: ActionResult::Success,
+        event_data: serde_json::json!({
+            "suspension_id": suspension.id,
+            "notes": request.notes,
+        }),
+        ip_address: Some(addr.ip()),
+        compliance_tags: vec!["admin".to_string(), "account_suspension".to_string()],
+        ..Default::default()
+    }).await?;
+
+    Ok(Json(suspension))
+}
+
+/// GET /api/admin/users/:id/suspensions - Suspension history of a user, newest first
+///
+/// Requires: admin or superadmin role
+#[utoipa::path(
+    get,
+    path = "/api/admin/users/{id}/suspensions",
+    tag = "admin",
+    summary = "Suspension history of a user, newest first",
+    params(
+        ("id" = String, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<UserSuspension>),
+    )
+)]
+pub async fn get_user_suspensions(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(user_id): Path<String>,
+) -> Result<Json<Vec<UserSuspension>>> {
+    require_admin!(claims);
+
+    // Parse user ID
+    let user_id = Uuid::parse_str(&user_id)
+        .map_err(|_| AppError::BadRequest("Invalid user ID format".to_string()))?;
+
+    let suspensions = UserSuspensionService::new(config.database_pool.clone())
+        .history(user_id)
+        .await?;
+
+    Ok(Json(suspensions))
+}
+
+/// POST /api/admin/listings/takedown - Take down listings in bulk
+///
+/// Request body:
+/// ```json
+/// {
+///   "inventory_ids": ["..."],
+///   "user_id": "...",
+///   "reason": "Recalled lot"
+/// }
+/// ```
+///
+/// Takes down the given listings, every listing of `user_id`, or both (at most
+/// 500 ids per request). Owners are notified once each.
+///
+/// Requires: admin or superadmin role
+#[utoipa::path(
+    post,
+    path = "/api/admin/listings/takedown",
+    tag = "admin",
+    summary = "Take down listings in bulk",
+    request_body = ListingTakedownRequest,
+    responses(
+        (status = 200, description = "Success", body = ListingTakedownResult),
+    )
+)]
+pub async fn take_down_listings(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    axum::extract::ConnectInfo(addr): axum::extract::ConnectInfo<std::net::SocketAddr>,
+    Json(request): Json<ListingTakedownRequest>,
+) -> Result<Json<ListingTakedownResult>> {
+    require_admin!(claims);
+
+    let reason = request.validate().map_err(AppError::BadRequest)?;
+    let result = UserSuspensionService::new(config.database_pool.clone())
+        .take_down_listings(&request, &reason, claims.user_id)
+        .await?;
+
+    ComprehensiveAuditService::new(config.database_pool.clone()).log(AuditLogEntry {
+        event_type: "admin_listings_taken_down".to_string(),
+        event_category: EventCategory::Admin,
+        severity: Severity::Warning,
+        actor_user_id: Some(claims.user_id),
+        actor_type: "user".to_string(),
+        resource_type: Some("inventory".to_string()),
+        resource_id: request.user_id.map(|user_id| user_id.to_string()),
+        action: "take_down_listings".to_string(),
+        action_result: ActionResult::Success,
+        event_data: serde_json::json!({
+            "reason": reason,
+            "requested_ids": request.inventory_ids.len(),
+            "user_id": request.user_id,
+            "taken_down": result.taken_down,
+        }),
+        ip_address: Some(addr.ip()),
+        compliance_tags: vec!["admin".to_string(), "listing_takedown".to_string()],
+        ..Default::default()
+    }).await?;
+
+    Ok(Json(result))
+}
+
+/// POST /api/admin/listings/restore - Put taken-down listings back on the marketplace
+///
+/// Request body:
+/// ```json
+/// {
+///   "inventory_ids": ["..."]
+/// }
+/// ```
+///
+/// Requires: admin or superadmin role
+#[utoipa::path(
+    post,
+    path = "/api/admin/listings/restore",
+    tag = "admin",
+    summary = "Put taken-down listings back on the marketplace",
+    request_body = ListingRestoreRequest,
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
+pub async fn restore_listings(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    axum::extract::ConnectInfo(addr): axum::extract::ConnectInfo<std::net::SocketAddr>,
+    Json(request): Json<ListingRestoreRequest>,
+) -> Result<Json<serde_json::Value>> {
+    require_admin!(claims);
+
+    request.validate().map_err(AppError::BadRequest)?;
+    let restored = UserSuspensionService::new(config.database_pool.clone())
+        .restore_listings(&request.inventory_ids)
+        .await?;
+
+    ComprehensiveAuditService::new(config.database_pool.clone()).log(AuditLogEntry {
+        event_type: "admin_listings_restored".to_string(),
+        event_category: EventCategory::Admin,
+        severity: Severity::Info,
+        actor_user_id: Some(claims.user_id),
+        actor_type: "user".to_string(),
+        resource_type: Some("inventory".to_string()),
+        action: "restore_listings".to_string(),
+        action_result: ActionResult::Success,
+        event_data: serde_json::json!({ "restored": restored }),
+        ip_address: Some(addr.ip()),
+        compliance_tags: vec!["admin".to_string(), "listing_takedown".to_string()],
+        ..Default::default()
+    }).await?;
+
+    Ok(Json(serde_json::json!({ "restored": restored })))
+}
+
 // ============================================================================
 // VERIFICATION QUEUE ENDPOINTS
 // ============================================================================
 
 /// GET /api/admin/verification-queue - Get pending verification queue
 ///
-/// Returns list of unverified users with context (inventory count, transaction count, waiting time)
+/// Returns list of unverified, unrejected users with context (inventory count,
+/// transaction count, waiting time) and their uploaded licenses
 ///
 /// Requires: admin or superadmin role
+#[utoipa::path(
+    get,
+    path = "/api/admin/verification-queue",
+    tag = "admin",
+    summary = "Get pending verification queue",
+    responses(
+        (status = 200, description = "Success", body = Vec<VerificationQueueItem>),
+    )
+)]
 pub async fn get_verification_queue(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -259,14 +656,56 @@ pub async fn get_verification_queue(
     let admin_service = AdminService::new(user_repo, audit_service);
 
     // Get queue
-    let queue = admin_service.get_verification_queue(
+    let mut queue = admin_service.get_verification_queue(
         claims.user_id,
         ip_address.map(|ip| ip.to_string()),
     ).await?;
 
+    // Attach license documents for review
+    VerificationService::new(config.database_pool.clone())
+        .attach_licenses(&mut queue)
+        .await?;
+
     Ok(Json(queue))
 }
 
+/// POST /api/admin/verification-queue/decisions - Approve or reject applicants in bulk
+///
+/// Request body:
+/// ```json
+/// {
+///   "decisions": [
+///     { "user_id": "...", "decision": "approve" },
+///     { "user_id": "...", "decision": "reject", "reason": "License number not found" }
+///   ]
+/// }
+/// ```
+///
+/// Each decision is applied on its own; applicants that are no longer pending are
+/// reported as skipped. Applicants are notified of the outcome.
+///
+/// Requires: admin or superadmin role
+#[utoipa::path(
+    post,
+    path = "/api/admin/verification-queue/decisions",
+    tag = "admin",
+    summary = "Approve or reject applicants in bulk",
+    request_body = BulkVerificationRequest,
+    responses(
+        (status = 200, description = "Success", body = BulkVerificationResult),
+    )
+)]
+pub async fn decide_verifications(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<BulkVerificationRequest>,
+) -> Result<Json<BulkVerificationResult>> {
+    let service = VerificationService::new(config.database_pool.clone());
+    let result = service.decide_bulk(&request, claims.user_id).await?;
+
+    Ok(Json(result))
+}
+
 // ============================================================================
 // STATISTICS ENDPOINTS
 // ============================================================================
@@ -279,20 +718,32 @@ pub async fn get_verification_queue(
 /// - Recent signups (last 7 days)
 /// - System health metrics
 ///
+/// Trends over time (funnels, active users, GMV) are under /api/admin/analytics.
+///
 /// Requires: admin or superadmin role
+#[utoipa::path(
+    get,
+    path = "/api/admin/stats",
+    tag = "admin",
+    summary = "Get admin dashboard statistics",
+    responses(
+        (status = 200, description = "Success", body = AdminStatsResponse),
+    )
+)]
 pub async fn get_admin_stats(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
 ) -> Result<Json<AdminStatsResponse>> {
-    // Create admin service
-    let user_repo = UserRepository::new(config.database_pool.clone(), &config.encryption_key)?;
+    // Counts may lag the primary by a few seconds, so they come from the read replica
+    let read_pool = config.read_pool(ReadConsistency::Relaxed);
+    let user_repo = UserRepository::new(read_pool.clone(), &config.encryption_key)?;
     let audit_service = ComprehensiveAuditService::new(config.database_pool.clone());
     let admin_service = AdminService::new(user_repo, audit_service);
 
     // Get stats
     let stats = admin_service.get_admin_stats(
         claims.user_id,
-        &config.database_pool,
+        &read_pool,
     ).await?;
 
     Ok(Json(stats))
@@ -313,6 +764,18 @@ pub async fn get_admin_stats(
 /// - end_date: ISO 8601 datetime
 ///
 /// Requires: admin or superadmin role
+#[utoipa::path(
+    get,
+    path = "/api/admin/audit-logs",
+    tag = "admin",
+    summary = "Get audit logs with filters",
+    params(
+        AuditLogQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<AuditLogResponse>),
+    )
+)]
 pub async fn get_audit_logs(
     State(config): State<AppConfig>,
     Extension(_claims): Extension<Claims>,
@@ -332,19 +795,299 @@ pub async fn get_audit_logs(
     Ok(Json(logs))
 }
 
+// ============================================================================
+// AI RESPONSE CACHE ENDPOINTS
+// ============================================================================
+
+/// GET /api/admin/ai-cache/stats - AI response cache usage per feature
+///
+/// Returns live/expired entries, hits, and the tokens and API cost saved by hits.
+/// Live hit/miss counters are also exported as `atlas_ai_cache_lookups_total`.
+///
+/// Requires: admin or superadmin role
+#[utoipa::path(
+    get,
+    path = "/api/admin/ai-cache/stats",
+    tag = "admin",
+    summary = "AI response cache usage per feature",
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
+pub async fn get_ai_cache_stats(
+    State(config): State<AppConfig>,
+    Extension(_claims): Extension<Claims>,
+) -> Result<Json<serde_json::Value>> {
+    let cache = AiResponseCacheService::new(config.database_pool.clone());
+    let stats = cache.stats().await?;
+
+    Ok(Json(serde_json::json!({
+        "enabled": cache.config().enabled,
+        "disabled_features": cache.config().disabled_features,
+        "features": stats,
+    })))
+}
+
+#[derive(Debug, serde::Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PurgeAiCacheQuery {
+    /// Drop every entry of this feature instead of only expired entries
+    pub feature: Option<String>,
+}
+
+/// POST /api/admin/ai-cache/purge - Remove expired (or one feature's) cache entries
+///
+/// Requires: admin or superadmin role
+#[utoipa::path(
+    post,
+    path = "/api/admin/ai-cache/purge",
+    tag = "admin",
+    summary = "Remove expired (or one feature's) cache entries",
+    params(
+        PurgeAiCacheQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
+pub async fn purge_ai_cache(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<PurgeAiCacheQuery>,
+) -> Result<Json<serde_json::Value>> {
+    let removed = AiResponseCacheService::new(config.database_pool.clone())
+        .purge(query.feature.as_deref())
+        .await?;
+
+    tracing::info!(
+        "AI response cache purged by {}: {} entries (feature: {})",
+        claims.user_id,
+        removed,
+        query.feature.as_deref().unwrap_or("expired only")
+    );
+
+    Ok(Json(serde_json::json!({ "removed": removed })))
+}
+
+// ============================================================================
+// QUERY CACHE ENDPOINTS
+// ============================================================================
+
+/// GET /api/admin/query-cache/stats - Catalog and search query cache usage
+///
+/// Returns entries, hits, misses and hit rate per cache for the instance serving the
+/// request. Hit/miss counters of every instance are exported as
+/// `atlas_query_cache_lookups_total`.
+///
+/// Requires: admin or superadmin role
+#[utoipa::path(
+    get,
+    path = "/api/admin/query-cache/stats",
+    tag = "admin",
+    summary = "Catalog and search query cache usage",
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
+pub async fn get_query_cache_stats(
+    Extension(_claims): Extension<Claims>,
+) -> Result<Json<serde_json::Value>> {
+    Ok(Json(serde_json::json!({
+        "enabled": QueryCache::config().enabled,
+        "caches": QueryCache::stats(),
+    })))
+}
+
+#[derive(Debug, serde::Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PurgeQueryCacheQuery {
+    /// Flush only this cache instead of all of them
+    pub cache: Option<String>,
+}
+
+/// POST /api/admin/query-cache/purge - Flush the query caches of this instance
+///
+/// Requires: admin or superadmin role
+#[utoipa::path(
+    post,
+    path = "/api/admin/query-cache/purge",
+    tag = "admin",
+    summary = "Flush the query caches of this instance",
+    params(
+        PurgeQueryCacheQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
+pub async fn purge_query_cache(
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<PurgeQueryCacheQuery>,
+) -> Result<Json<serde_json::Value>> {
+    let caches = match query.cache.as_deref() {
+        Some(name) => vec![QueryCacheKind::parse(name)
+            .ok_or_else(|| AppError::BadRequest(format!("Unknown cache: {}", name)))?],
+        None => QueryCacheKind::ALL.to_vec(),
+    };
+    for kind in &caches {
+        QueryCache::invalidate(*kind, "manual");
+    }
+
+    tracing::info!(
+        "Query cache purged by {}: {}",
+        claims.user_id,
+        query.cache.as_deref().unwrap_or("all caches")
+    );
+
+    Ok(Json(serde_json::json!({
+        "purged": caches.iter().map(|kind| kind.as_str()).collect::<Vec<_>>(),
+    })))
+}
+
+// ============================================================================
+// SEARCH ENGINE ENDPOINTS
+// ============================================================================
+
+/// GET /api/admin/search-engine/status - Configured search engine and index state
+///
+/// Per index: whether it is enabled and ready (searches use Postgres until it is),
+/// queued changes, the oldest queued change and the engine's document count.
+///
+/// Requires: admin or superadmin role
+#[utoipa::path(
+    get,
+    path = "/api/admin/search-engine/status",
+    tag = "admin",
+    summary = "Configured search engine and index state",
+    responses(
+        (status = 200, description = "Success", body = SearchEngineStatus),
+    )
+)]
+pub async fn get_search_engine_status(
+    State(config): State<AppConfig>,
+    Extension(_claims): Extension<Claims>,
+) -> Result<Json<SearchEngineStatus>> {
+    let service = SearchIndexService::new(config.database_pool.clone(), config.search_engine.clone());
+    Ok(Json(service.status().await?))
+}
+
+/// POST /api/admin/search-engine/reindex - Re-send every record to the search engine
+///
+/// Body: `{"index": "marketplace" | "openfda" | "ema"}`; all indexes when omitted.
+/// Searches keep using the engine while the worker drains the queue.
+///
+/// Requires: admin or superadmin role
+#[utoipa::path(
+    post,
+    path = "/api/admin/search-engine/reindex",
+    tag = "admin",
+    summary = "Re-send every record to the search engine",
+    request_body = ReindexRequest,
+    responses(
+        (status = 200, description = "Success", body = ReindexResponse),
+    )
+)]
+pub async fn reindex_search_engine(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<ReindexRequest>,
+) -> Result<Json<ReindexResponse>> {
+    let index = request.index.as_deref().map(SearchIndex::parse).transpose()?;
+    let service = SearchIndexService::new(config.database_pool.clone(), config.search_engine.clone());
+    let response = service.reindex(index).await?;
+
+    tracing::info!(
+        "Search reindex requested by {}: {}",
+        claims.user_id,
+        request.index.as_deref().unwrap_or("all indexes")
+    );
+
+    Ok(Json(response))
+}
+
+// ============================================================================
+// ROUTE POLICY ENDPOINTS
+// ============================================================================
+
+/// GET /api/admin/route-policies - Middleware protecting each API route
+///
+/// Lists every route with its policies (auth, admin, superadmin, rate_limit,
+/// auth_rate_limit), why public routes are public, and any policy violations.
+/// The report is built once at startup from the router definition.
+///
+/// Requires: admin or superadmin role
+#[utoipa::path(
+    get,
+    path = "/api/admin/route-policies",
+    tag = "admin",
+    summary = "Middleware protecting each API route",
+    responses(
+        (status = 200, description = "Success", body = RoutePolicyReport),
+    )
+)]
+pub async fn get_route_policies(
+    Extension(_claims): Extension<Claims>,
+) -> Result<Json<&'static RoutePolicyReport>> {
+    route_policy_report()
+        .map(Json)
+        .ok_or_else(|| AppError::NotFound("Route policy report was not built at startup".to_string()))
+}
+
 // ============================================================================
 // HEALTH CHECK ENDPOINT (No auth required)
 // ============================================================================
 
-/// GET /api/admin/health - Admin API health check
+/// GET /api/admin/health - Readiness with per-dependency status
+/// GET /api/admin/health/ready
+///
+/// Checks the database, encryption key, file storage, Claude API and ERP scheduler and
+/// reports each one's status and latency. 200 when ok or degraded (a non-essential
+/// dependency failing), 503 when down.
 ///
-/// Returns 200 OK if admin API is operational
+/// No authentication required (for monitoring systems)
+#[utoipa::path(
+    get,
+    path = "/api/admin/health",
+    tag = "admin",
+    operation_id = "admin_health_check",
+    summary = "Readiness with per-dependency status",
+    responses(
+        (status = 200, description = "Ok or degraded", body = crate::models::health::HealthReport),
+        (status = 503, description = "Down", body = crate::models::health::HealthReport),
+    )
+)]
+pub async fn health_check(State(config): State<AppConfig>) -> Response {
+    let report = HealthService::new(&config).readiness().await;
+
+    if report.status != HealthStatus::Ok {
+        let failing: Vec<&str> = report
+            .checks
+            .iter()
+            .filter(|check| check.status != HealthStatus::Ok)
+            .map(|check| check.name)
+            .collect();
+        tracing::warn!("Health check {:?}: {}", report.status, failing.join(", "));
+    }
+
+    let status = match report.status {
+        HealthStatus::Down => StatusCode::SERVICE_UNAVAILABLE,
+        _ => StatusCode::OK,
+    };
+    (status, Json(report)).into_response()
+}
+
+/// GET /api/admin/health/live - Liveness (the process is serving requests)
 ///
 /// No authentication required (for monitoring systems)
-pub async fn health_check() -> impl IntoResponse {
-    Json(serde_json::json!({
-        "status": "ok",
-        "service": "admin_api",
-        "timestamp": chrono::Utc::now(),
-    }))
+#[utoipa::path(
+    get,
+    path = "/api/admin/health/live",
+    tag = "admin",
+    summary = "Liveness (the process is serving requests)",
+    responses(
+        (status = 200, description = "The process is serving requests", body = crate::models::health::HealthReport),
+    )
+)]
+pub async fn liveness_check() -> impl IntoResponse {
+    Json(HealthService::liveness())
 }
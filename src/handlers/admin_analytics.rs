@@ -0,0 +1,115 @@
+/// REST API handlers for admin analytics (funnels, active users, GMV)
+///
+/// Every endpoint takes `from`, `to`, `interval` (day|week|month) and
+/// `format` (json|csv); CSV downloads hold one row per bucket.
+
+use axum::{
+    extract::{Query, State},
+    http::{header, HeaderValue},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use crate::{
+    config::{AppConfig, ReadConsistency},
+    middleware::error_handling::{AppError, Result},
+    models::admin_analytics::*,
+    services::AdminAnalyticsService,
+};
+
+/// GET /api/admin/analytics/funnel
+/// Registration → verification → first listing → first transaction, by signup cohort
+#[utoipa::path(
+    get,
+    path = "/api/admin/analytics/funnel",
+    tag = "admin_analytics",
+    summary = "Registration → verification → first listing → first transaction, by signup cohort",
+    params(
+        AnalyticsQuery,
+    ),
+    responses(
+        (status = 200, description = "The report; its rows as CSV with format=csv", body = FunnelReport),
+    )
+)]
+pub async fn get_funnel(
+    State(config): State<AppConfig>,
+    Query(query): Query<AnalyticsQuery>,
+) -> Result<Response> {
+    let range = resolve(&query)?;
+    let report = AdminAnalyticsService::new(config.read_pool(ReadConsistency::Relaxed)).funnel(&range).await?;
+    respond(&range, "funnel", &report.cohorts, &report)
+}
+
+/// GET /api/admin/analytics/active-users
+/// Distinct active sellers and buyers per bucket (weekly by default)
+#[utoipa::path(
+    get,
+    path = "/api/admin/analytics/active-users",
+    tag = "admin_analytics",
+    summary = "Distinct active sellers and buyers per bucket (weekly by default)",
+    params(
+        AnalyticsQuery,
+    ),
+    responses(
+        (status = 200, description = "The report; its rows as CSV with format=csv", body = ActiveUsersReport),
+    )
+)]
+pub async fn get_active_users(
+    State(config): State<AppConfig>,
+    Query(query): Query<AnalyticsQuery>,
+) -> Result<Response> {
+    let range = resolve(&query)?;
+    let report = AdminAnalyticsService::new(config.read_pool(ReadConsistency::Relaxed)).active_users(&range).await?;
+    respond(&range, "active_users", &report.buckets, &report)
+}
+
+/// GET /api/admin/analytics/gmv
+/// Completed transaction value by bucket, buyer country and category
+#[utoipa::path(
+    get,
+    path = "/api/admin/analytics/gmv",
+    tag = "admin_analytics",
+    summary = "Completed transaction value by bucket, buyer country and category",
+    params(
+        AnalyticsQuery,
+    ),
+    responses(
+        (status = 200, description = "The report; its rows as CSV with format=csv", body = GmvReport),
+    )
+)]
+pub async fn get_gmv(
+    State(config): State<AppConfig>,
+    Query(query): Query<AnalyticsQuery>,
+) -> Result<Response> {
+    let range = resolve(&query)?;
+    let report = AdminAnalyticsService::new(config.read_pool(ReadConsistency::Relaxed)).gmv(&range).await?;
+    respond(&range, "gmv", &report.buckets, &report)
+}
+
+fn resolve(query: &AnalyticsQuery) -> Result<AnalyticsRange> {
+    query.resolve(chrono::Utc::now()).map_err(AppError::BadRequest)
+}
+
+/// The report as JSON, or its rows as a CSV download
+fn respond<R: AnalyticsRow, T: Serialize>(range: &AnalyticsRange, name: &str, rows: &[R], report: &T) -> Result<Response> {
+    if range.format == AnalyticsFormat::Json {
+        return Ok(Json(report).into_response());
+    }
+
+    let mut response = Response::new(encode_csv(rows)?.into());
+    let headers = response.headers_mut();
+
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("text/csv; charset=utf-8"));
+    let filename = format!(
+        "atlas_{}_{}_{}_{}.csv",
+        name,
+        range.interval.as_str(),
+        range.from.format("%Y%m%d"),
+        range.to.format("%Y%m%d"),
+    );
+    if let Ok(value) = HeaderValue::from_str(&format!("attachment; filename=\"{}\"", filename)) {
+        headers.insert(header::CONTENT_DISPOSITION, value);
+    }
+
+    Ok(response)
+}
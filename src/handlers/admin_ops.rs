@@ -0,0 +1,32 @@
+/// Admin Operations Dashboard Handlers
+///
+/// One endpoint for on-call engineers with the state of the job queue, schedulers, rate
+/// limiters, webhook deliveries, AI spend and database pool. Rate limiters, the ERP
+/// scheduler and the pool are in-process, so they describe the instance that answered.
+
+use axum::{extract::State, Extension, Json};
+use crate::{
+    config::AppConfig,
+    middleware::{error_handling::Result, Claims},
+    models::ops_dashboard::OpsDashboard,
+    services::OpsDashboardService,
+};
+
+/// GET /api/admin/ops
+#[utoipa::path(
+    get,
+    path = "/api/admin/ops",
+    tag = "admin_ops",
+    responses(
+        (status = 200, description = "Success", body = OpsDashboard),
+    )
+)]
+pub async fn get_ops_dashboard(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<OpsDashboard>> {
+    crate::require_admin!(claims);
+
+    let service = OpsDashboardService::new(config.database_pool.clone());
+    Ok(Json(service.overview().await?))
+}
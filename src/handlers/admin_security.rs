@@ -10,6 +10,8 @@
 // - PUT  /api/admin/security/quotas/:id     - Update user quota tier
 // - GET  /api/admin/security/encryption     - Encryption key rotation status
 // - POST /api/admin/security/encryption/rotate - Trigger key rotation
+// - GET  /api/admin/security/encryption/reencryption - Re-encryption progress per table
+// - POST /api/admin/security/encryption/reencrypt - Queue re-encryption with the active key
 // - GET  /api/admin/security/metrics        - Prometheus metrics summary
 // - GET  /api/admin/security/rate-limits    - Rate limiting overview
 //
@@ -21,6 +23,7 @@ use axum::{
 };
 use chrono::{DateTime, Utc, Datelike};
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 use sqlx::PgPool;
 use std::collections::HashMap;
 use uuid::Uuid;
@@ -28,9 +31,12 @@ use uuid::Uuid;
 use crate::{
     config::AppConfig,
     middleware::{auth::Claims, error_handling::{AppError, Result}},
+    models::background_job::{BackgroundJob, BackgroundJobQuery, EncryptionReencryptJob, JobType, NewJob},
     services::{
         api_quota_service::{ApiQuotaService, QuotaTier},
-        encryption_key_rotation_service::EncryptionKeyRotationService,
+        encryption_key_rotation_service::{EncryptionKeyRotationService, KEYRING_REFRESH_SECONDS},
+        encryption_reencryption_service::{percent_migrated, EncryptionReencryptionService, TableReencryptionProgress},
+        BackgroundJobService, EncryptionService,
         comprehensive_audit_service::{ComprehensiveAuditService, AuditLogEntry, EventCategory, Severity, ActionResult},
     },
 };
@@ -40,7 +46,8 @@ use crate::{
 // ============================================================================
 
 /// API Usage Query Filters
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct ApiUsageFilters {
     pub user_id: Option<Uuid>,
     pub endpoint: Option<String>,
@@ -51,7 +58,7 @@ pub struct ApiUsageFilters {
 }
 
 /// API Usage Record Response
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ApiUsageRecord {
     pub id: Uuid,
     pub user_id: Uuid,
@@ -66,7 +73,7 @@ pub struct ApiUsageRecord {
 }
 
 /// API Usage Analytics Response
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ApiUsageAnalytics {
     pub total_requests: i64,
     pub total_cost_cents: f64,
@@ -78,7 +85,7 @@ pub struct ApiUsageAnalytics {
     pub recent_requests: Vec<ApiUsageRecord>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct EndpointUsage {
     pub endpoint: String,
     pub request_count: i64,
@@ -86,7 +93,7 @@ pub struct EndpointUsage {
     pub avg_latency_ms: f64,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct UserUsage {
     pub user_id: Uuid,
     pub user_email: String,
@@ -95,7 +102,7 @@ pub struct UserUsage {
     pub quota_tier: QuotaTier,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct TimeSeriesPoint {
     pub date: String,
     pub requests: i64,
@@ -103,7 +110,7 @@ pub struct TimeSeriesPoint {
 }
 
 /// User Quota Info
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct UserQuotaInfo {
     pub user_id: Uuid,
     pub user_email: String,
@@ -117,13 +124,13 @@ pub struct UserQuotaInfo {
 }
 
 /// Quota Update Request
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct QuotaUpdateRequest {
     pub quota_tier: QuotaTier,
 }
 
 /// Encryption Key Info
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct EncryptionKeyInfo {
     pub id: Uuid,
     pub key_version: i32,
@@ -136,7 +143,7 @@ pub struct EncryptionKeyInfo {
 }
 
 /// Encryption Status Response
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct EncryptionStatus {
     pub active_key: EncryptionKeyInfo,
     pub rotation_status: String, // "OK", "SOON", "OVERDUE"
@@ -145,7 +152,7 @@ pub struct EncryptionStatus {
     pub rotation_history: Vec<KeyRotationEvent>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct KeyRotationEvent {
     pub id: Uuid,
     pub old_version: i32,
@@ -156,13 +163,25 @@ pub struct KeyRotationEvent {
 }
 
 /// Key Rotation Request
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct KeyRotationRequest {
     pub reason: Option<String>,
 }
 
+/// Re-encryption Progress Response
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReencryptionStatus {
+    /// Key version new data is encrypted with (0 = ENCRYPTION_KEY itself)
+    pub active_key_version: i32,
+    /// Share of encrypted rows across all tables already on the active key
+    pub percent_migrated: f64,
+    pub tables: Vec<TableReencryptionProgress>,
+    /// Latest re-encryption job
+    pub job: Option<BackgroundJob>,
+}
+
 /// Metrics Summary Response
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct MetricsSummary {
     pub http_requests_total: i64,
     pub http_requests_per_minute: f64,
@@ -179,14 +198,14 @@ pub struct MetricsSummary {
 }
 
 /// Rate Limit Status Response
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct RateLimitStatus {
     pub active_rate_limits: Vec<RateLimitEntry>,
     pub top_limited_ips: Vec<IpLimitInfo>,
     pub configuration: RateLimitConfig,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct RateLimitEntry {
     pub ip_address: String,
     pub current_tokens: i32,
@@ -194,14 +213,14 @@ pub struct RateLimitEntry {
     pub last_request: DateTime<Utc>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct IpLimitInfo {
     pub ip_address: String,
     pub hit_count: i64,
     pub last_hit: DateTime<Utc>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct RateLimitConfig {
     pub auth_limit: String,      // "5 requests per 15 minutes"
     pub api_limit: String,        // "100 requests per minute"
@@ -217,6 +236,18 @@ pub struct RateLimitConfig {
 /// Returns API usage analytics with filters
 /// Note: Admin authorization is handled by middleware
 ///
+#[utoipa::path(
+    get,
+    path = "/api/admin/security/api-usage",
+    tag = "admin_security",
+    summary = "Returns API usage analytics with filters",
+    params(
+        ApiUsageFilters,
+    ),
+    responses(
+        (status = 200, description = "Success", body = ApiUsageAnalytics),
+    )
+)]
 pub async fn get_api_usage_analytics(
     State(config): State<AppConfig>,
     Extension(_claims): Extension<Claims>,
@@ -409,6 +440,15 @@ pub async fn get_api_usage_analytics(
 /// Returns all users' quota tiers and usage
 /// Note: Admin authorization is handled by middleware
 ///
+#[utoipa::path(
+    get,
+    path = "/api/admin/security/quotas",
+    tag = "admin_security",
+    summary = "Returns all users' quota tiers and usage",
+    responses(
+        (status = 200, description = "Success", body = Vec<UserQuotaInfo>),
+    )
+)]
 pub async fn get_user_quotas(
     State(config): State<AppConfig>,
     Extension(_claims): Extension<Claims>,
@@ -473,6 +513,19 @@ pub async fn get_user_quotas(
 /// Update user's quota tier
 /// Note: Superadmin authorization is handled by middleware
 ///
+#[utoipa::path(
+    put,
+    path = "/api/admin/security/quotas/{user_id}",
+    tag = "admin_security",
+    summary = "Update user's quota tier",
+    params(
+        ("user_id" = Uuid, Path),
+    ),
+    request_body = QuotaUpdateRequest,
+    responses(
+        (status = 200, description = "Success", body = UserQuotaInfo),
+    )
+)]
 pub async fn update_user_quota(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -562,6 +615,15 @@ pub async fn update_user_quota(
 /// Returns encryption key rotation status
 /// Note: Admin authorization is handled by middleware
 ///
+#[utoipa::path(
+    get,
+    path = "/api/admin/security/encryption",
+    tag = "admin_security",
+    summary = "Returns encryption key rotation status",
+    responses(
+        (status = 200, description = "Success", body = EncryptionStatus),
+    )
+)]
 pub async fn get_encryption_status(
     State(config): State<AppConfig>,
     Extension(_claims): Extension<Claims>,
@@ -662,6 +724,16 @@ pub async fn get_encryption_status(
 /// Trigger manual encryption key rotation
 /// Note: Superadmin authorization is handled by middleware
 ///
+#[utoipa::path(
+    post,
+    path = "/api/admin/security/encryption/rotate",
+    tag = "admin_security",
+    summary = "Trigger manual encryption key rotation",
+    request_body = KeyRotationRequest,
+    responses(
+        (status = 200, description = "Success", body = EncryptionKeyInfo),
+    )
+)]
 pub async fn rotate_encryption_key(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -681,6 +753,11 @@ pub async fn rotate_encryption_key(
     let age_days = (now - new_key.created_at).num_days();
     let days_until_expiry = (new_key.valid_until - now).num_days();
 
+    // Existing data moves to the new key once every instance encrypts with it
+    let activates_in = EncryptionKeyRotationService::activation_delay().as_secs() + KEYRING_REFRESH_SECONDS;
+    let reencrypt_at = now + chrono::Duration::seconds(activates_in as i64);
+    let reencrypt_job = enqueue_reencryption(&config, new_key.key_version, claims.user_id, Some(reencrypt_at)).await?;
+
     // Audit log
     let audit_service = ComprehensiveAuditService::new(config.database_pool.clone());
     audit_service.log(AuditLogEntry {
@@ -696,6 +773,7 @@ pub async fn rotate_encryption_key(
         event_data: serde_json::json!({
             "new_key_version": new_key.key_version,
             "reason": request.reason.clone().unwrap_or_else(|| "Manual rotation".to_string()),
+            "reencrypt_job_id": reencrypt_job.id,
         }),
         ip_address: None,
         is_pii_access: false,
@@ -721,11 +799,111 @@ pub async fn rotate_encryption_key(
     }))
 }
 
+/// GET /api/admin/security/encryption/reencryption
+///
+/// Share of encrypted rows per table already on the active key, and the latest
+/// re-encryption job
+/// Note: Admin authorization is handled by middleware
+///
+#[utoipa::path(
+    get,
+    path = "/api/admin/security/encryption/reencryption",
+    tag = "admin_security",
+    summary = "Share of encrypted rows per table already on the active key, and the latest",
+    responses(
+        (status = 200, description = "Success", body = ReencryptionStatus),
+    )
+)]
+pub async fn get_reencryption_status(
+    State(config): State<AppConfig>,
+    Extension(_claims): Extension<Claims>,
+) -> Result<Json<ReencryptionStatus>> {
+    // Authorization handled by admin_middleware
+
+    let active_key_version = EncryptionService::new(&config.encryption_key)?.active_key_version();
+    let tables = EncryptionReencryptionService::new(config.database_pool.clone(), &config.encryption_key)
+        .progress(active_key_version)
+        .await?;
+
+    let (encrypted_rows, current_rows) = tables
+        .iter()
+        .fold((0, 0), |(encrypted, current), table| (encrypted + table.encrypted_rows, current + table.current_rows));
+    let percent_migrated = percent_migrated(encrypted_rows, current_rows);
+
+    let job = BackgroundJobService::new(config.database_pool.clone())
+        .list(&BackgroundJobQuery {
+            job_type: Some(JobType::EncryptionReencrypt.as_str().to_string()),
+            status: None,
+            limit: Some(1),
+            offset: None,
+        })
+        .await?
+        .into_iter()
+        .next();
+
+    Ok(Json(ReencryptionStatus { active_key_version, percent_migrated, tables, job }))
+}
+
+/// POST /api/admin/security/encryption/reencrypt
+///
+/// Queue re-encryption of existing data with the active key, e.g. after a failed job
+/// or for data written before key versions existed
+/// Note: Superadmin authorization is handled by middleware
+///
+#[utoipa::path(
+    post,
+    path = "/api/admin/security/encryption/reencrypt",
+    tag = "admin_security",
+    summary = "Queue re-encryption of existing data with the active key, e.g. after a failed job",
+    responses(
+        (status = 200, description = "Success", body = BackgroundJob),
+    )
+)]
+pub async fn start_reencryption(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<BackgroundJob>> {
+    // Authorization handled by superadmin_middleware
+
+    let key_version = EncryptionService::new(&config.encryption_key)?.active_key_version();
+    let job = enqueue_reencryption(&config, key_version, claims.user_id, None).await?;
+
+    tracing::info!("🔐 Re-encryption with key version {} queued by admin {}", key_version, claims.user_id);
+
+    Ok(Json(job))
+}
+
+/// Queue the re-encryption job of a key version; an unfinished one is returned instead
+async fn enqueue_reencryption(
+    config: &AppConfig,
+    key_version: i32,
+    user_id: Uuid,
+    run_at: Option<DateTime<Utc>>,
+) -> Result<BackgroundJob> {
+    let mut job = NewJob::new(JobType::EncryptionReencrypt, &EncryptionReencryptJob { key_version })
+        .with_dedupe_key(format!("encryption_reencrypt:{}", key_version))
+        .created_by(user_id);
+    if let Some(run_at) = run_at {
+        job = job.run_at(run_at);
+    }
+
+    BackgroundJobService::new(config.database_pool.clone()).enqueue(job).await
+}
+
 /// GET /api/admin/security/metrics
 ///
 /// Returns Prometheus metrics summary for admin UI
 /// Note: Admin authorization is handled by middleware
 ///
+#[utoipa::path(
+    get,
+    path = "/api/admin/security/metrics",
+    tag = "admin_security",
+    summary = "Returns Prometheus metrics summary for admin UI",
+    responses(
+        (status = 200, description = "Success", body = MetricsSummary),
+    )
+)]
 pub async fn get_metrics_summary(
     State(_config): State<AppConfig>,
     Extension(_claims): Extension<Claims>,
@@ -759,6 +937,15 @@ pub async fn get_metrics_summary(
 /// Returns current rate limiting state
 /// Note: Admin authorization is handled by middleware
 ///
+#[utoipa::path(
+    get,
+    path = "/api/admin/security/rate-limits",
+    tag = "admin_security",
+    summary = "Returns current rate limiting state",
+    responses(
+        (status = 200, description = "Success", body = RateLimitStatus),
+    )
+)]
 pub async fn get_rate_limit_status(
     State(_config): State<AppConfig>,
     Extension(_claims): Extension<Claims>,
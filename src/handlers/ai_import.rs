@@ -2,26 +2,59 @@
 
 use axum::{
     extract::{State, Multipart, Path, Query},
+    http::StatusCode,
     Extension,
     Json,
 };
 use uuid::Uuid;
+use validator::Validate;
 use crate::{
     config::AppConfig,
     middleware::{error_handling::Result, Claims},
-    models::ai_import::*,
+    models::{ai_import::*, background_job::{AiImportJob, JobType, NewJob}},
     services::{
         AiImportService,
+        DocumentExtractionService,
         FileParserService,
-        BatchImportProcessor,
         AuditService,
         ApiQuotaService,
+        AiQuotaService,
+        BackgroundJobService,
     },
     utils::encrypted_file_storage::EncryptedFileStorage,
 };
+use utoipa::{IntoParams, ToSchema};
+
+/// 🔒 SECURITY: Check API quota before making an Anthropic API call
+async fn check_api_quota(quota_service: &ApiQuotaService, user_id: Uuid) -> Result<()> {
+    let (allowed, used, remaining) = quota_service.check_quota(user_id).await?;
+
+    if !allowed {
+        tracing::warn!("API quota exceeded for user: {} (used: {}, remaining: {:?})",
+            user_id, used, remaining);
+        return Err(crate::middleware::error_handling::AppError::Forbidden(
+            format!("API quota exceeded. You have used {} requests this month.", used)
+        ));
+    }
+
+    tracing::info!("API quota check passed for user: {} (used: {}, remaining: {:?})",
+        user_id, used, remaining);
+
+    Ok(())
+}
 
 /// POST /api/ai-import/upload
 /// Upload and analyze a file for import
+#[utoipa::path(
+    post,
+    path = "/api/ai-import/upload",
+    tag = "ai_import",
+    summary = "Upload and analyze a file for import",
+    request_body(content = crate::openapi::ImportUploadForm, content_type = "multipart/form-data"),
+    responses(
+        (status = 200, description = "Success", body = ImportSessionResponse),
+    )
+)]
 pub async fn upload_and_analyze(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -35,11 +68,13 @@ pub async fn upload_and_analyze(
             anyhow::anyhow!("ANTHROPIC_API_KEY not configured")
         ))?;
 
-    let ai_service = AiImportService::new(config.database_pool.clone(), claude_api_key);
+    let ai_service = AiImportService::new(config.database_pool.clone(), claude_api_key.clone());
 
     // Parse multipart form data
     let mut file_data: Option<Vec<u8>> = None;
     let mut filename: Option<String> = None;
+    let mut supplier_name: Option<String> = None;
+    let mut force_analysis = false;
 
     while let Some(field) = multipart.next_field().await.map_err(|e| {
         crate::middleware::error_handling::AppError::InvalidInput(format!("Invalid multipart data: {}", e))
@@ -51,6 +86,21 @@ pub async fn upload_and_analyze(
             file_data = Some(field.bytes().await.map_err(|e| {
                 crate::middleware::error_handling::AppError::InvalidInput(format!("Failed to read file: {}", e))
             })?.to_vec());
+        } else if field_name == "supplier_name" || field_name == "force_analysis" {
+            let value = field.text().await.map_err(|e| {
+                crate::middleware::error_handling::AppError::InvalidInput(format!("Invalid {} field: {}", field_name, e))
+            })?;
+            let value = value.trim();
+            if field_name == "supplier_name" {
+                if value.len() > 255 {
+                    return Err(crate::middleware::error_handling::AppError::InvalidInput(
+                        "supplier_name must be at most 255 characters".to_string()
+                    ));
+                }
+                supplier_name = Some(value.to_string()).filter(|v| !v.is_empty());
+            } else {
+                force_analysis = matches!(value, "true" | "1");
+            }
         }
     }
 
@@ -93,50 +143,65 @@ pub async fn upload_and_analyze(
     tracing::info!("File saved to: {}",
         crate::utils::log_sanitizer::sanitize_for_log(&file_path));
 
-    // Update session with file path and hash
+    // Update session with file path, hash and supplier
     sqlx::query(
-        "UPDATE ai_import_sessions SET file_path = $1, file_hash = $2 WHERE id = $3"
+        "UPDATE ai_import_sessions SET file_path = $1, file_hash = $2, supplier_name = $3 WHERE id = $4"
     )
     .bind(&file_path)
     .bind(&file_hash)
+    .bind(&supplier_name)
     .bind(session_id)
     .execute(&config.database_pool)
     .await?;
 
-    // 🔒 SECURITY: Check API quota before making Anthropic API call
     let quota_service = ApiQuotaService::new(config.database_pool.clone());
-    let (allowed, used, remaining) = quota_service.check_quota(claims.user_id).await?;
-
-    if !allowed {
-        tracing::warn!("API quota exceeded for user: {} (used: {}, remaining: {:?})",
-            claims.user_id, used, remaining);
-        return Err(crate::middleware::error_handling::AppError::Forbidden(
-            format!("API quota exceeded. You have used {} requests this month.", used)
-        ));
-    }
-
-    tracing::info!("API quota check passed for user: {} (used: {}, remaining: {:?})",
-        claims.user_id, used, remaining);
 
-    // Analyze file with Claude AI (in background for large files)
+    // Analyze file with Claude AI: tabular files get a column mapping, PDFs and images
+    // (COAs, price lists, packing lists) a field-by-field extraction for review.
+    // A tabular file whose header row matches a confirmed mapping reuses it without an AI call.
     let start_time = std::time::Instant::now();
-    let _mapping = ai_service.analyze_file(session_id, file_data.clone(), claims.user_id).await?;
+    let endpoint = match FileParserService::document_media_type(&file_data, &filename) {
+        Some(media_type) => {
+            check_api_quota(&quota_service, claims.user_id).await?;
+            let extraction_service = DocumentExtractionService::new(config.database_pool.clone(), claude_api_key);
+            extraction_service.extract_document(session_id, &file_data, media_type, claims.user_id).await?;
+            Some("ai_import/document_extraction")
+        }
+        None => {
+            let parsed_file = ai_service.prepare_file(session_id, &file_data).await?;
+            let remembered = if force_analysis {
+                None
+            } else {
+                ai_service.apply_remembered_mapping(session_id, claims.user_id, &parsed_file).await?
+            };
+
+            if remembered.is_some() {
+                None
+            } else {
+                check_api_quota(&quota_service, claims.user_id).await?;
+                ai_service.analyze_file(session_id, &parsed_file, claims.user_id).await?;
+                Some("ai_import/file_analysis")
+            }
+        }
+    };
     let latency_ms = start_time.elapsed().as_millis() as i64;
 
-    // 📊 OBSERVABILITY: Track API usage
-    let estimated_tokens_input = (file_data.len() / 4) as i32; // Rough estimate: 1 token ≈ 4 bytes
-    let estimated_tokens_output = 500; // Rough estimate for AI response
-
-    quota_service.record_usage(
-        claims.user_id,
-        "ai_import/file_analysis",
-        estimated_tokens_input,
-        estimated_tokens_output,
-        latency_ms as i32,
-    ).await?;
-
-    tracing::info!("API usage tracked for user: {} (input tokens: ~{}, output tokens: ~{})",
-        claims.user_id, estimated_tokens_input, estimated_tokens_output);
+    // 📊 OBSERVABILITY: Track API usage (nothing to track when a remembered mapping was reused)
+    if let Some(endpoint) = endpoint {
+        let estimated_tokens_input = (file_data.len() / 4) as i32; // Rough estimate: 1 token ≈ 4 bytes
+        let estimated_tokens_output = 500; // Rough estimate for AI response
+
+        quota_service.record_usage(
+            claims.user_id,
+            endpoint,
+            estimated_tokens_input,
+            estimated_tokens_output,
+            latency_ms as i32,
+        ).await?;
+
+        tracing::info!("API usage tracked for user: {} (input tokens: ~{}, output tokens: ~{})",
+            claims.user_id, estimated_tokens_input, estimated_tokens_output);
+    }
 
     // Get updated session
     let session = ai_service.get_session(session_id).await?;
@@ -148,6 +213,19 @@ pub async fn upload_and_analyze(
 
 /// GET /api/ai-import/session/:id
 /// Get import session details
+#[utoipa::path(
+    get,
+    path = "/api/ai-import/session/{id}",
+    tag = "ai_import",
+    operation_id = "ai_import_get_session",
+    summary = "Get import session details",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = ImportSessionResponse),
+    )
+)]
 pub async fn get_session(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -172,7 +250,20 @@ pub async fn get_session(
 }
 
 /// POST /api/ai-import/session/:id/start-import
-/// Start the actual import process after mapping approval
+/// Start the actual import process after mapping approval. The rows are imported in the
+/// background; the session moves from `importing` to `completed` (or `failed`).
+#[utoipa::path(
+    post,
+    path = "/api/ai-import/session/{id}/start-import",
+    tag = "ai_import",
+    summary = "Start the actual import process after mapping approval. The rows are imported in the",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = ImportSessionResponse),
+    )
+)]
 pub async fn start_import(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -212,68 +303,381 @@ pub async fn start_import(
         anyhow::anyhow!("Failed to parse mapping: {}", e)
     ))?;
 
-    // Get file path from session
-    let file_path = session.file_path
+    // Starting the import confirms the mapping: remember it for later files with this header row
+    ai_service.remember_mapping(&session, &mapping).await?;
+
+    if session.file_path.is_none() {
+        return Err(crate::middleware::error_handling::AppError::BadRequest(
+            "No file available for this session".to_string()
+        ));
+    }
+
+    // Update session status to importing; the status check keeps a session from starting twice
+    let started = sqlx::query(
+        "UPDATE ai_import_sessions SET status = 'importing', import_started_at = NOW() WHERE id = $1 AND status = 'mapping_review'"
+    )
+    .bind(session_id)
+    .execute(&config.database_pool)
+    .await?;
+    if started.rows_affected() == 0 {
+        return Err(crate::middleware::error_handling::AppError::Conflict);
+    }
+
+    // The rows are imported by a background job, which survives restarts and resumes
+    // after the last committed batch
+    let job = AiImportJob { session_id, user_id: claims.user_id };
+    let queued = BackgroundJobService::new(config.database_pool.clone())
+        .enqueue(
+            NewJob::new(JobType::AiImport, &job)
+                .with_dedupe_key(format!("ai_import:{}", session_id))
+                .created_by(claims.user_id)
+        )
+        .await;
+    if let Err(e) = queued {
+        sqlx::query("UPDATE ai_import_sessions SET status = 'mapping_review' WHERE id = $1 AND status = 'importing'")
+            .bind(session_id)
+            .execute(&config.database_pool)
+            .await?;
+        return Err(e);
+    }
+
+    tracing::info!("Import of session {} queued", session_id);
+
+    // Return updated session
+    let updated_session = ai_service.get_session(session_id).await?;
+    Ok(Json(updated_session.into()))
+}
+
+/// GET /api/ai-import/session/:id/preview
+/// Rows as they will be imported with the suggested mapping, with their accept/reject decisions
+#[utoipa::path(
+    get,
+    path = "/api/ai-import/session/{id}/preview",
+    tag = "ai_import",
+    summary = "Rows as they will be imported with the suggested mapping, with their accept/reject decisions",
+    params(
+        ("id" = Uuid, Path),
+        PreviewRowsQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = RowPreviewResponse),
+    )
+)]
+pub async fn preview_rows(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(session_id): Path<Uuid>,
+    Query(params): Query<PreviewRowsQuery>,
+) -> Result<Json<RowPreviewResponse>> {
+    let claude_api_key = std::env::var("ANTHROPIC_API_KEY")
+        .map_err(|_| crate::middleware::error_handling::AppError::Internal(
+            anyhow::anyhow!("ANTHROPIC_API_KEY not configured")
+        ))?;
+
+    let ai_service = AiImportService::new(config.database_pool.clone(), claude_api_key);
+    let session = ai_service.get_session(session_id).await?;
+
+    if session.user_id != claims.user_id {
+        return Err(crate::middleware::error_handling::AppError::Forbidden(
+            "Access denied".to_string()
+        ));
+    }
+
+    let mapping: ColumnMapping = session.ai_mapping.clone()
+        .and_then(|m| serde_json::from_value(m).ok())
+        .ok_or_else(|| crate::middleware::error_handling::AppError::BadRequest(
+            "No mapping available for this session".to_string()
+        ))?;
+
+    let file_path = session.file_path.as_deref()
         .ok_or_else(|| crate::middleware::error_handling::AppError::BadRequest(
             "No file available for this session".to_string()
         ))?;
 
-    // 🔒 PRODUCTION SECURITY: Load and decrypt file from disk
     let file_storage = EncryptedFileStorage::new(
         &config.file_storage_path,
         &config.encryption_key
     )?;
-    let file_data = file_storage.read_encrypted_file(&file_path)?;
+    let file_data = file_storage.read_encrypted_file(file_path)?;
+    let parsed_file = FileParserService::parse(&file_data, &session.original_filename)?;
 
-    // 🔒 SECURITY: Sanitize file path for log injection prevention
-    tracing::info!("Loaded file from storage: {} ({} bytes)",
-        crate::utils::log_sanitizer::sanitize_for_log(&file_path),
-        file_data.len());
+    let limit = params.limit.unwrap_or(100).clamp(1, 500) as usize;
+    let offset = params.offset.unwrap_or(0).max(0) as usize;
 
-    // Update session status to importing
-    sqlx::query!(
-        "UPDATE ai_import_sessions SET status = 'importing', import_started_at = NOW() WHERE id = $1",
-        session_id
+    Ok(Json(ai_service.preview_rows(&session, &parsed_file, &mapping, offset, limit).await?))
+}
+
+/// PUT /api/ai-import/session/:id/row-decisions
+/// Accept or reject individual rows before import; rejected rows are skipped
+#[utoipa::path(
+    put,
+    path = "/api/ai-import/session/{id}/row-decisions",
+    tag = "ai_import",
+    summary = "Accept or reject individual rows before import; rejected rows are skipped",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    request_body = UpdateRowDecisionsRequest,
+    responses(
+        (status = 200, description = "Success", body = Vec<AiImportRowDecision>),
     )
-    .execute(&config.database_pool)
-    .await?;
+)]
+pub async fn update_row_decisions(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(session_id): Path<Uuid>,
+    Json(request): Json<UpdateRowDecisionsRequest>,
+) -> Result<Json<Vec<AiImportRowDecision>>> {
+    request.validate().map_err(|e| {
+        crate::middleware::error_handling::AppError::InvalidInput(format!("Invalid row decisions: {}", e))
+    })?;
 
-    // Parse the file
-    let parsed_file = FileParserService::parse(&file_data, &session.original_filename)?;
+    let claude_api_key = std::env::var("ANTHROPIC_API_KEY")
+        .map_err(|_| crate::middleware::error_handling::AppError::Internal(
+            anyhow::anyhow!("ANTHROPIC_API_KEY not configured")
+        ))?;
+
+    let ai_service = AiImportService::new(config.database_pool.clone(), claude_api_key);
+    let session = ai_service.get_session(session_id).await?;
+
+    if session.user_id != claims.user_id {
+        return Err(crate::middleware::error_handling::AppError::Forbidden(
+            "Access denied".to_string()
+        ));
+    }
 
-    tracing::info!("File parsed: {} rows", parsed_file.rows.len());
+    let decisions = ai_service.set_row_decisions(&session, claims.user_id, &request.decisions).await?;
 
-    // Process import with batch processor
-    let batch_processor = BatchImportProcessor::new(config.database_pool.clone());
-    let stats = batch_processor.process_import(
+    let rejected = request.decisions.iter().filter(|d| d.decision == RowDecision::Reject).count();
+    AuditService::new(config.database_pool.clone()).log_rows_reviewed(
         session_id,
         claims.user_id,
-        parsed_file,
-        mapping,
+        request.decisions.len() - rejected,
+        rejected,
     ).await?;
 
-    tracing::info!(
-        "Import completed for session {}: {} imported, {} failed",
+    Ok(Json(decisions))
+}
+
+/// POST /api/ai-import/session/:id/rollback
+/// Reverse the stock a completed import created
+#[utoipa::path(
+    post,
+    path = "/api/ai-import/session/{id}/rollback",
+    tag = "ai_import",
+    summary = "Reverse the stock a completed import created",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = RollbackResult),
+    )
+)]
+pub async fn rollback_import(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(session_id): Path<Uuid>,
+) -> Result<Json<RollbackResult>> {
+    tracing::info!("Rolling back import session: {}", session_id);
+
+    let claude_api_key = std::env::var("ANTHROPIC_API_KEY")
+        .map_err(|_| crate::middleware::error_handling::AppError::Internal(
+            anyhow::anyhow!("ANTHROPIC_API_KEY not configured")
+        ))?;
+
+    let ai_service = AiImportService::new(config.database_pool.clone(), claude_api_key);
+    let session = ai_service.get_session(session_id).await?;
+
+    if session.user_id != claims.user_id {
+        return Err(crate::middleware::error_handling::AppError::Forbidden(
+            "Access denied".to_string()
+        ));
+    }
+
+    let result = ai_service.rollback_import(&session, claims.user_id).await?;
+
+    AuditService::new(config.database_pool.clone()).log_import_rolled_back(
         session_id,
-        stats.rows_imported,
-        stats.rows_failed
-    );
+        claims.user_id,
+        result.rows_rolled_back,
+        result.quantity_reversed,
+        result.skipped.len(),
+    ).await?;
 
-    // Update session status to completed
-    sqlx::query!(
-        "UPDATE ai_import_sessions SET status = 'completed', import_completed_at = NOW() WHERE id = $1",
-        session_id
+    Ok(Json(result))
+}
+
+/// GET /api/ai-import/mappings
+/// Column mappings remembered from confirmed imports, reused for files with the same header row
+#[utoipa::path(
+    get,
+    path = "/api/ai-import/mappings",
+    tag = "ai_import",
+    summary = "Column mappings remembered from confirmed imports, reused for files with the same header row",
+    responses(
+        (status = 200, description = "Success", body = Vec<AiImportMappingMemory>),
     )
-    .execute(&config.database_pool)
-    .await?;
+)]
+pub async fn list_remembered_mappings(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Vec<AiImportMappingMemory>>> {
+    let claude_api_key = std::env::var("ANTHROPIC_API_KEY")
+        .map_err(|_| crate::middleware::error_handling::AppError::Internal(
+            anyhow::anyhow!("ANTHROPIC_API_KEY not configured")
+        ))?;
 
-    // Return updated session
-    let updated_session = ai_service.get_session(session_id).await?;
-    Ok(Json(updated_session.into()))
+    let ai_service = AiImportService::new(config.database_pool.clone(), claude_api_key);
+    Ok(Json(ai_service.list_mapping_memory(claims.user_id).await?))
+}
+
+/// DELETE /api/ai-import/mappings/:id
+/// Forget a remembered mapping so the next matching upload gets a fresh AI analysis
+#[utoipa::path(
+    delete,
+    path = "/api/ai-import/mappings/{id}",
+    tag = "ai_import",
+    summary = "Forget a remembered mapping so the next matching upload gets a fresh AI analysis",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 204, description = "No content"),
+    )
+)]
+pub async fn delete_remembered_mapping(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(memory_id): Path<Uuid>,
+) -> Result<StatusCode> {
+    let claude_api_key = std::env::var("ANTHROPIC_API_KEY")
+        .map_err(|_| crate::middleware::error_handling::AppError::Internal(
+            anyhow::anyhow!("ANTHROPIC_API_KEY not configured")
+        ))?;
+
+    let ai_service = AiImportService::new(config.database_pool.clone(), claude_api_key);
+    ai_service.delete_mapping_memory(claims.user_id, memory_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /api/ai-import/session/:id/extraction
+/// Extracted document fields with per-field confidence and the fields awaiting review
+#[utoipa::path(
+    get,
+    path = "/api/ai-import/session/{id}/extraction",
+    tag = "ai_import",
+    summary = "Extracted document fields with per-field confidence and the fields awaiting review",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = DocumentExtractionResponse),
+    )
+)]
+pub async fn get_extraction(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(session_id): Path<Uuid>,
+) -> Result<Json<DocumentExtractionResponse>> {
+    let service = owned_extraction_session(&config, &claims, session_id).await?.0;
+    let (record, extraction) = service.get_extraction(session_id).await?;
+
+    Ok(Json(DocumentExtractionResponse::new(&record, extraction)))
+}
+
+/// PUT /api/ai-import/session/:id/extraction
+/// Confirm or correct extracted fields (and optionally the detected document kind)
+#[utoipa::path(
+    put,
+    path = "/api/ai-import/session/{id}/extraction",
+    tag = "ai_import",
+    summary = "Confirm or correct extracted fields (and optionally the detected document kind)",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    request_body = ReviewExtractionRequest,
+    responses(
+        (status = 200, description = "Success", body = DocumentExtractionResponse),
+    )
+)]
+pub async fn review_extraction(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(session_id): Path<Uuid>,
+    Json(request): Json<ReviewExtractionRequest>,
+) -> Result<Json<DocumentExtractionResponse>> {
+    let service = owned_extraction_session(&config, &claims, session_id).await?.0;
+    let (record, extraction) = service.review_extraction(session_id, claims.user_id, &request).await?;
+
+    Ok(Json(DocumentExtractionResponse::new(&record, extraction)))
+}
+
+/// POST /api/ai-import/session/:id/extraction/apply
+/// Import a reviewed price or packing list into inventory, link a COA to its lots,
+/// or attach the document to a marketplace transaction
+#[utoipa::path(
+    post,
+    path = "/api/ai-import/session/{id}/extraction/apply",
+    tag = "ai_import",
+    summary = "Import a reviewed price or packing list into inventory, link a COA to its lots,",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    request_body = ApplyExtractionRequest,
+    responses(
+        (status = 200, description = "Success", body = DocumentExtractionResponse),
+    )
+)]
+pub async fn apply_extraction(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(session_id): Path<Uuid>,
+    Json(request): Json<ApplyExtractionRequest>,
+) -> Result<Json<DocumentExtractionResponse>> {
+    let (service, session) = owned_extraction_session(&config, &claims, session_id).await?;
+    let (record, extraction) = service.apply_extraction(&session, claims.user_id, &request).await?;
+
+    Ok(Json(DocumentExtractionResponse::new(&record, extraction)))
+}
+
+/// Load a session the caller owns, with the extraction service for it
+async fn owned_extraction_session(
+    config: &AppConfig,
+    claims: &Claims,
+    session_id: Uuid,
+) -> Result<(DocumentExtractionService, AiImportSession)> {
+    let claude_api_key = std::env::var("ANTHROPIC_API_KEY")
+        .map_err(|_| crate::middleware::error_handling::AppError::Internal(
+            anyhow::anyhow!("ANTHROPIC_API_KEY not configured")
+        ))?;
+
+    let ai_service = AiImportService::new(config.database_pool.clone(), claude_api_key.clone());
+    let session = ai_service.get_session(session_id).await?;
+
+    if session.user_id != claims.user_id {
+        return Err(crate::middleware::error_handling::AppError::Forbidden(
+            "Access denied".to_string()
+        ));
+    }
+
+    Ok((DocumentExtractionService::new(config.database_pool.clone(), claude_api_key), session))
 }
 
 /// GET /api/ai-import/sessions
 /// List user's import sessions
+#[utoipa::path(
+    get,
+    path = "/api/ai-import/sessions",
+    tag = "ai_import",
+    summary = "List user's import sessions",
+    params(
+        ListSessionsQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<ImportSessionResponse>),
+    )
+)]
 pub async fn list_sessions(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -305,6 +709,19 @@ pub async fn list_sessions(
 
 /// GET /api/ai-import/session/:id/rows
 /// Get detailed row results for a session
+#[utoipa::path(
+    get,
+    path = "/api/ai-import/session/{id}/rows",
+    tag = "ai_import",
+    summary = "Get detailed row results for a session",
+    params(
+        ("id" = Uuid, Path),
+        GetRowsQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<AiImportRowResult>),
+    )
+)]
 pub async fn get_session_rows(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -370,10 +787,24 @@ pub async fn get_session_rows(
 
 /// GET /api/ai-import/quota
 /// Get user's AI usage quota and limits
+#[utoipa::path(
+    get,
+    path = "/api/ai-import/quota",
+    tag = "ai_import",
+    summary = "Get user's AI usage quota and limits",
+    responses(
+        (status = 200, description = "Success", body = UserQuotaResponse),
+    )
+)]
 pub async fn get_user_quota(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
 ) -> Result<Json<UserQuotaResponse>> {
+    // Creates the limits row for new users, so the defaults below are a fallback only
+    let usage = AiQuotaService::new(config.database_pool.clone())
+        .get_usage(claims.user_id)
+        .await?;
+
     let quota = sqlx::query!(
         r#"
         SELECT
@@ -401,6 +832,7 @@ pub async fn get_user_quota(
             cost_remaining_usd: format!("{:.4}", (q.monthly_ai_cost_limit_usd - q.monthly_ai_cost_used_usd).max(rust_decimal::Decimal::ZERO)),
             period_start: q.limit_period_start.to_string(),
             period_end: q.limit_period_end.to_string(),
+            usage,
         }
     } else {
         // Default quota for new users
@@ -413,6 +845,7 @@ pub async fn get_user_quota(
             cost_remaining_usd: "10.0000".to_string(),
             period_start: chrono::Utc::now().date_naive().to_string(),
             period_end: (chrono::Utc::now() + chrono::Duration::days(30)).date_naive().to_string(),
+            usage,
         }
     };
 
@@ -423,20 +856,29 @@ pub async fn get_user_quota(
 // Request/Response Models
 // ============================================================================
 
-#[derive(serde::Deserialize)]
+#[derive(serde::Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct ListSessionsQuery {
     pub limit: Option<i32>,
     pub offset: Option<i32>,
 }
 
-#[derive(serde::Deserialize)]
+#[derive(serde::Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PreviewRowsQuery {
+    pub limit: Option<i32>,
+    pub offset: Option<i32>,
+}
+
+#[derive(Debug, serde::Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct GetRowsQuery {
     pub limit: Option<i32>,
     pub offset: Option<i32>,
     pub status_filter: Option<String>,
 }
 
-#[derive(serde::Serialize)]
+#[derive(serde::Serialize, ToSchema)]
 pub struct UserQuotaResponse {
     pub monthly_import_limit: i32,
     pub monthly_imports_used: i32,
@@ -446,4 +888,6 @@ pub struct UserQuotaResponse {
     pub cost_remaining_usd: String,
     pub period_start: String,
     pub period_end: String,
+    /// Every AI feature's limit and usage under the user's plan tier
+    pub usage: crate::models::AiQuotaUsage,
 }
@@ -0,0 +1,262 @@
+/// REST API handlers for AI plan tiers, quota top-ups and admin overrides
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension,
+    Json,
+};
+use uuid::Uuid;
+use crate::{
+    config::AppConfig,
+    middleware::{error_handling::{Result, AppError}, Claims},
+    models::ai_quota::*,
+    services::{
+        comprehensive_audit_service::{ActionResult, AuditLogEntry, ComprehensiveAuditService, EventCategory, Severity},
+        AiQuotaService,
+    },
+};
+
+fn parse_feature(feature: &str) -> Result<QuotaFeature> {
+    QuotaFeature::from_str(feature)
+        .ok_or_else(|| AppError::BadRequest(format!("Unknown AI quota feature '{}'", feature)))
+}
+
+async fn audit_quota_change(
+    config: &AppConfig,
+    claims: &Claims,
+    user_id: Uuid,
+    action: &str,
+    event_data: serde_json::Value,
+) -> Result<()> {
+    ComprehensiveAuditService::new(config.database_pool.clone()).log(AuditLogEntry {
+        event_type: "admin_ai_quota_update".to_string(),
+        event_category: EventCategory::Admin,
+        severity: Severity::Warning,
+        actor_user_id: Some(claims.user_id),
+        actor_type: "user".to_string(),
+        resource_type: Some("ai_quota".to_string()),
+        resource_id: Some(user_id.to_string()),
+        action: action.to_string(),
+        action_result: ActionResult::Success,
+        event_data,
+        ip_address: None,
+        is_pii_access: false,
+        compliance_tags: vec!["admin".to_string()],
+        ..Default::default()
+    }).await?;
+
+    Ok(())
+}
+
+// ============================================================================
+// User Endpoints
+// ============================================================================
+
+/// GET /api/ai-quota
+/// Plan tier and per-feature limits, usage and top-ups for the current period
+#[utoipa::path(
+    get,
+    path = "/api/ai-quota",
+    tag = "ai_quota",
+    operation_id = "ai_quota_get_usage",
+    summary = "Plan tier and per-feature limits, usage and top-ups for the current period",
+    responses(
+        (status = 200, description = "Success", body = AiQuotaUsage),
+    )
+)]
+pub async fn get_usage(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<AiQuotaUsage>> {
+    let service = AiQuotaService::new(config.database_pool.clone());
+    Ok(Json(service.get_usage(claims.user_id).await?))
+}
+
+/// GET /api/ai-quota/tiers
+/// Available plan tiers and their monthly limits
+#[utoipa::path(
+    get,
+    path = "/api/ai-quota/tiers",
+    tag = "ai_quota",
+    summary = "Available plan tiers and their monthly limits",
+    responses(
+        (status = 200, description = "Success", body = Vec<AiQuotaTier>),
+    )
+)]
+pub async fn list_tiers(
+    State(config): State<AppConfig>,
+) -> Result<Json<Vec<AiQuotaTier>>> {
+    let service = AiQuotaService::new(config.database_pool.clone());
+    Ok(Json(service.list_tiers().await?))
+}
+
+/// POST /api/ai-quota/topups
+/// Buy extra quota for one feature until the end of the current period
+#[utoipa::path(
+    post,
+    path = "/api/ai-quota/topups",
+    tag = "ai_quota",
+    summary = "Buy extra quota for one feature until the end of the current period",
+    request_body = PurchaseTopupRequest,
+    responses(
+        (status = 201, description = "Created", body = PurchaseTopupResponse),
+    )
+)]
+pub async fn purchase_topup(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<PurchaseTopupRequest>,
+) -> Result<(StatusCode, Json<PurchaseTopupResponse>)> {
+    let service = AiQuotaService::new(config.database_pool.clone());
+    let response = service.purchase_topup(claims.user_id, &request).await?;
+
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+/// GET /api/ai-quota/topups
+/// User's top-up history, newest first
+#[utoipa::path(
+    get,
+    path = "/api/ai-quota/topups",
+    tag = "ai_quota",
+    summary = "User's top-up history, newest first",
+    responses(
+        (status = 200, description = "Success", body = Vec<AiQuotaTopup>),
+    )
+)]
+pub async fn list_topups(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Vec<AiQuotaTopup>>> {
+    let service = AiQuotaService::new(config.database_pool.clone());
+    Ok(Json(service.list_topups(claims.user_id).await?))
+}
+
+// ============================================================================
+// Admin Endpoints
+// ============================================================================
+
+/// GET /api/admin/ai-quota/:user_id
+/// A user's AI quota breakdown
+#[utoipa::path(
+    get,
+    path = "/api/admin/ai-quota/{user_id}",
+    tag = "ai_quota",
+    operation_id = "ai_quota_admin_get_usage",
+    summary = "A user's AI quota breakdown",
+    params(
+        ("user_id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = AiQuotaUsage),
+    )
+)]
+pub async fn admin_get_usage(
+    State(config): State<AppConfig>,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<AiQuotaUsage>> {
+    let service = AiQuotaService::new(config.database_pool.clone());
+    Ok(Json(service.get_usage(user_id).await?))
+}
+
+/// PUT /api/admin/ai-quota/:user_id/tier
+/// Move a user to another plan tier (superadmin)
+#[utoipa::path(
+    put,
+    path = "/api/admin/ai-quota/{user_id}/tier",
+    tag = "ai_quota",
+    summary = "Move a user to another plan tier (superadmin)",
+    params(
+        ("user_id" = Uuid, Path),
+    ),
+    request_body = SetPlanTierRequest,
+    responses(
+        (status = 200, description = "Success", body = AiQuotaUsage),
+    )
+)]
+pub async fn admin_set_tier(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(user_id): Path<Uuid>,
+    Json(request): Json<SetPlanTierRequest>,
+) -> Result<Json<AiQuotaUsage>> {
+    let service = AiQuotaService::new(config.database_pool.clone());
+    let usage = service.set_tier(user_id, &request.tier).await?;
+
+    audit_quota_change(&config, &claims, user_id, "set_ai_plan_tier", serde_json::json!({
+        "user_id": user_id,
+        "new_tier": request.tier,
+    })).await?;
+
+    Ok(Json(usage))
+}
+
+/// PUT /api/admin/ai-quota/:user_id/overrides/:feature
+/// Replace the tier limit of one feature for a user (superadmin)
+#[utoipa::path(
+    put,
+    path = "/api/admin/ai-quota/{user_id}/overrides/{feature}",
+    tag = "ai_quota",
+    summary = "Replace the tier limit of one feature for a user (superadmin)",
+    params(
+        ("user_id" = Uuid, Path),
+        ("feature" = String, Path),
+    ),
+    request_body = SetQuotaOverrideRequest,
+    responses(
+        (status = 200, description = "Success", body = AiQuotaOverride),
+    )
+)]
+pub async fn admin_set_override(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path((user_id, feature)): Path<(Uuid, String)>,
+    Json(request): Json<SetQuotaOverrideRequest>,
+) -> Result<Json<AiQuotaOverride>> {
+    let feature = parse_feature(&feature)?;
+    let service = AiQuotaService::new(config.database_pool.clone());
+    let record = service.set_override(user_id, feature, &request, claims.user_id).await?;
+
+    audit_quota_change(&config, &claims, user_id, "set_ai_quota_override", serde_json::json!({
+        "user_id": user_id,
+        "feature": feature,
+        "monthly_limit": request.monthly_limit,
+        "reason": request.reason,
+        "expires_at": request.expires_at,
+    })).await?;
+
+    Ok(Json(record))
+}
+
+/// DELETE /api/admin/ai-quota/:user_id/overrides/:feature
+/// Restore the tier limit of one feature for a user (superadmin)
+#[utoipa::path(
+    delete,
+    path = "/api/admin/ai-quota/{user_id}/overrides/{feature}",
+    tag = "ai_quota",
+    summary = "Restore the tier limit of one feature for a user (superadmin)",
+    params(
+        ("user_id" = Uuid, Path),
+        ("feature" = String, Path),
+    ),
+    responses(
+        (status = 204, description = "No content"),
+    )
+)]
+pub async fn admin_remove_override(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path((user_id, feature)): Path<(Uuid, String)>,
+) -> Result<StatusCode> {
+    let feature = parse_feature(&feature)?;
+    let service = AiQuotaService::new(config.database_pool.clone());
+    service.remove_override(user_id, feature).await?;
+
+    audit_quota_change(&config, &claims, user_id, "remove_ai_quota_override", serde_json::json!({
+        "user_id": user_id,
+        "feature": feature,
+    })).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
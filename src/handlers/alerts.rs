@@ -1,18 +1,26 @@
 /// Alert System REST API Handlers
 ///
-/// HTTP endpoints for alert notifications, preferences, and watchlist management.
+/// HTTP endpoints for alert notifications (including the real-time stream), preferences,
+/// watchlist management and the Slack / Microsoft Teams channels alerts are routed to.
 
 use axum::{
     extract::{State, Path, Query},
+    response::{sse::{Event, Sse}, IntoResponse},
     Extension,
     Json,
 };
+use futures::{stream, StreamExt};
+use std::{convert::Infallible, sync::Arc, time::Duration};
+use tokio::sync::broadcast::error::RecvError;
 use uuid::Uuid;
+use validator::Validate;
 use crate::{
     config::AppConfig,
-    middleware::{error_handling::Result, Claims},
-    models::alerts::*,
-    services::NotificationService,
+    middleware::{error_handling::{AppError, Result}, Claims},
+    models::{alert_channel::*, alerts::*, realtime::STREAM_HEARTBEAT_SECONDS},
+    services::{
+        AlertChannelService, NotificationService, RealtimeHub, RealtimeSubscription, Shutdown, TokenBlacklistService,
+    },
 };
 
 // ============================================================================
@@ -21,6 +29,18 @@ use crate::{
 
 /// GET /api/alerts/notifications
 /// Get user's notifications with optional filtering
+#[utoipa::path(
+    get,
+    path = "/api/alerts/notifications",
+    tag = "alerts",
+    summary = "Get user's notifications with optional filtering",
+    params(
+        GetNotificationsQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = NotificationSummary),
+    )
+)]
 pub async fn get_notifications(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -32,8 +52,66 @@ pub async fn get_notifications(
     Ok(Json(notifications))
 }
 
+/// GET /api/alerts/notifications/archived
+/// Notifications archived by the retention policy, paginated
+#[utoipa::path(
+    get,
+    path = "/api/alerts/notifications/archived",
+    tag = "alerts",
+    summary = "Notifications archived by the retention policy, paginated",
+    params(
+        ArchivedNotificationsQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = ArchivedNotificationsResponse),
+    )
+)]
+pub async fn get_archived_notifications(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<ArchivedNotificationsQuery>,
+) -> Result<Json<ArchivedNotificationsResponse>> {
+    let service = NotificationService::new(config.database_pool.clone());
+    Ok(Json(service.get_archived_notifications(claims.user_id, &query).await?))
+}
+
+/// POST /api/alerts/notifications/bulk-dismiss
+/// Dismiss notifications by type and/or age
+#[utoipa::path(
+    post,
+    path = "/api/alerts/notifications/bulk-dismiss",
+    tag = "alerts",
+    summary = "Dismiss notifications by type and/or age",
+    request_body = BulkDismissRequest,
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
+pub async fn bulk_dismiss_notifications(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<BulkDismissRequest>,
+) -> Result<Json<serde_json::Value>> {
+    let service = NotificationService::new(config.database_pool.clone());
+    let dismissed = service.bulk_dismiss(claims.user_id, &request).await?;
+
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "dismissed": dismissed
+    })))
+}
+
 /// GET /api/alerts/notifications/unread-count
 /// Get count of unread notifications
+#[utoipa::path(
+    get,
+    path = "/api/alerts/notifications/unread-count",
+    tag = "alerts",
+    summary = "Get count of unread notifications",
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
 pub async fn get_unread_count(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -48,6 +126,19 @@ pub async fn get_unread_count(
 
 /// PUT /api/alerts/notifications/:id/read
 /// Mark a notification as read/unread
+#[utoipa::path(
+    put,
+    path = "/api/alerts/notifications/{id}/read",
+    tag = "alerts",
+    summary = "Mark a notification as read/unread",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    request_body = MarkAlertReadRequest,
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
 pub async fn mark_notification_read(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -65,6 +156,15 @@ pub async fn mark_notification_read(
 
 /// POST /api/alerts/notifications/mark-all-read
 /// Mark all notifications as read for the user
+#[utoipa::path(
+    post,
+    path = "/api/alerts/notifications/mark-all-read",
+    tag = "alerts",
+    summary = "Mark all notifications as read for the user",
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
 pub async fn mark_all_read(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -81,6 +181,18 @@ pub async fn mark_all_read(
 
 /// DELETE /api/alerts/notifications/:id
 /// Dismiss (soft delete) a notification
+#[utoipa::path(
+    delete,
+    path = "/api/alerts/notifications/{id}",
+    tag = "alerts",
+    summary = "Dismiss (soft delete) a notification",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
 pub async fn dismiss_notification(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -95,12 +207,116 @@ pub async fn dismiss_notification(
     })))
 }
 
+// ============================================================================
+// REAL-TIME STREAM
+// ============================================================================
+
+struct StreamState {
+    subscription: RealtimeSubscription,
+    heartbeat: tokio::time::Interval,
+    claims: Claims,
+    blacklist: Arc<TokenBlacklistService>,
+    closed: bool,
+}
+
+/// GET /api/alerts/stream
+/// Server-sent events: `ready` (unread count) on connect, then `notification` and
+/// `inquiry_message` events as they happen, with a heartbeat comment when idle. `resync`
+/// means events were dropped because the client fell behind: refetch notifications.
+/// The stream ends with `reauthenticate` once the token expires or is revoked; the
+/// browser's EventSource reconnects with the refreshed auth cookie. It also ends when
+/// the instance shuts down, and the EventSource reconnects to another one.
+#[utoipa::path(
+    get,
+    path = "/api/alerts/stream",
+    tag = "alerts",
+    summary = "Server-sent events: `ready` (unread count) on connect, then `notification` and",
+    responses(
+        (status = 200, description = "Server-sent notification events", body = String, content_type = "text/event-stream"),
+    )
+)]
+pub async fn stream_notifications(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Extension(hub): Extension<RealtimeHub>,
+    Extension(blacklist): Extension<Arc<TokenBlacklistService>>,
+) -> Result<impl IntoResponse> {
+    let subscription = hub.subscribe(claims.user_id)?;
+
+    let service = NotificationService::new(config.database_pool.clone());
+    let unread_count = service.get_unread_count(claims.user_id).await?;
+    let ready = Event::default()
+        .event("ready")
+        .data(serde_json::json!({ "unread_count": unread_count }).to_string());
+
+    let period = Duration::from_secs(STREAM_HEARTBEAT_SECONDS);
+    let state = StreamState {
+        subscription,
+        heartbeat: tokio::time::interval_at(tokio::time::Instant::now() + period, period),
+        claims,
+        blacklist,
+        closed: false,
+    };
+
+    let events = stream::unfold(state, |mut state| async move {
+        if state.closed {
+            return None;
+        }
+        loop {
+            tokio::select! {
+                received = state.subscription.receiver.recv() => match received {
+                    Ok(event) if event.is_for(state.claims.user_id) => {
+                        let sse = Event::default()
+                            .event(event.kind.as_str())
+                            .id(event.id.to_string())
+                            .data(event.data.to_string());
+                        return Some((Ok(sse), state));
+                    }
+                    Ok(_) => continue,
+                    Err(RecvError::Lagged(skipped)) => {
+                        let sse = Event::default()
+                            .event("resync")
+                            .data(serde_json::json!({ "skipped": skipped }).to_string());
+                        return Some((Ok(sse), state));
+                    }
+                    Err(RecvError::Closed) => return None,
+                },
+                _ = Shutdown::wait() => return None,
+                _ = state.heartbeat.tick() => {
+                    let expired = chrono::Utc::now().timestamp() >= state.claims.exp as i64;
+                    let revoked = state.blacklist.is_blacklisted(&state.claims.jti)
+                        || state.blacklist.is_session_revoked(state.claims.user_id, state.claims.iat);
+                    if expired || revoked {
+                        state.closed = true;
+                        return Some((Ok(Event::default().event("reauthenticate").data("{}")), state));
+                    }
+                    return Some((Ok(Event::default().comment("heartbeat")), state));
+                }
+            }
+        }
+    });
+
+    let events = stream::once(async move { Ok::<_, Infallible>(ready) }).chain(events);
+
+    // Reverse proxies (nginx) must not buffer the stream
+    Ok(([("X-Accel-Buffering", "no")], Sse::new(events)))
+}
+
 // ============================================================================
 // ALERT PREFERENCES ENDPOINTS
 // ============================================================================
 
 /// GET /api/alerts/preferences
 /// Get user's alert preferences
+#[utoipa::path(
+    get,
+    path = "/api/alerts/preferences",
+    tag = "alerts",
+    summary = "Get user's alert preferences",
+    responses(
+        (status = 200, description = "Success", body = UserAlertPreferences),
+    )
+)]
 pub async fn get_preferences(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -113,6 +329,16 @@ pub async fn get_preferences(
 
 /// PUT /api/alerts/preferences
 /// Update user's alert preferences
+#[utoipa::path(
+    put,
+    path = "/api/alerts/preferences",
+    tag = "alerts",
+    summary = "Update user's alert preferences",
+    request_body = UpdateAlertPreferencesRequest,
+    responses(
+        (status = 200, description = "Success", body = UserAlertPreferences),
+    )
+)]
 pub async fn update_preferences(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -135,6 +361,15 @@ pub async fn update_preferences(
 
 /// GET /api/alerts/watchlist
 /// Get all watchlists for the user
+#[utoipa::path(
+    get,
+    path = "/api/alerts/watchlist",
+    tag = "alerts",
+    summary = "Get all watchlists for the user",
+    responses(
+        (status = 200, description = "Success", body = Vec<WatchlistResponse>),
+    )
+)]
 pub async fn get_watchlists(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -149,6 +384,16 @@ pub async fn get_watchlists(
 
 /// POST /api/alerts/watchlist
 /// Create a new watchlist
+#[utoipa::path(
+    post,
+    path = "/api/alerts/watchlist",
+    tag = "alerts",
+    summary = "Create a new watchlist",
+    request_body = CreateWatchlistRequest,
+    responses(
+        (status = 200, description = "Success", body = WatchlistResponse),
+    )
+)]
 pub async fn create_watchlist(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -168,6 +413,18 @@ pub async fn create_watchlist(
 
 /// GET /api/alerts/watchlist/:id
 /// Get a specific watchlist
+#[utoipa::path(
+    get,
+    path = "/api/alerts/watchlist/{id}",
+    tag = "alerts",
+    summary = "Get a specific watchlist",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = WatchlistResponse),
+    )
+)]
 pub async fn get_watchlist(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -181,6 +438,19 @@ pub async fn get_watchlist(
 
 /// PUT /api/alerts/watchlist/:id
 /// Update a watchlist
+#[utoipa::path(
+    put,
+    path = "/api/alerts/watchlist/{id}",
+    tag = "alerts",
+    summary = "Update a watchlist",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    request_body = UpdateWatchlistRequest,
+    responses(
+        (status = 200, description = "Success", body = WatchlistResponse),
+    )
+)]
 pub async fn update_watchlist(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -201,6 +471,18 @@ pub async fn update_watchlist(
 
 /// DELETE /api/alerts/watchlist/:id
 /// Delete a watchlist
+#[utoipa::path(
+    delete,
+    path = "/api/alerts/watchlist/{id}",
+    tag = "alerts",
+    summary = "Delete a watchlist",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
 pub async fn delete_watchlist(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -221,8 +503,70 @@ pub async fn delete_watchlist(
     })))
 }
 
+/// POST /api/alerts/watchlist/:id/pause
+/// Stop evaluating a watchlist (and its triggers) until resumed
+#[utoipa::path(
+    post,
+    path = "/api/alerts/watchlist/{id}/pause",
+    tag = "alerts",
+    summary = "Stop evaluating a watchlist (and its triggers) until resumed",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = WatchlistResponse),
+    )
+)]
+pub async fn pause_watchlist(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(watchlist_id): Path<Uuid>,
+) -> Result<Json<WatchlistResponse>> {
+    let service = NotificationService::new(config.database_pool.clone());
+    let watchlist = service.set_watchlist_paused(watchlist_id, claims.user_id, true).await?;
+
+    Ok(Json(watchlist.into()))
+}
+
+/// POST /api/alerts/watchlist/:id/resume
+/// Resume evaluation; the watchlist is checked on the next scheduler run
+#[utoipa::path(
+    post,
+    path = "/api/alerts/watchlist/{id}/resume",
+    tag = "alerts",
+    summary = "Resume evaluation; the watchlist is checked on the next scheduler run",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = WatchlistResponse),
+    )
+)]
+pub async fn resume_watchlist(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(watchlist_id): Path<Uuid>,
+) -> Result<Json<WatchlistResponse>> {
+    let service = NotificationService::new(config.database_pool.clone());
+    let watchlist = service.set_watchlist_paused(watchlist_id, claims.user_id, false).await?;
+
+    Ok(Json(watchlist.into()))
+}
+
 /// GET /api/alerts/watchlist/:id/matches
 /// Get matching marketplace items for a watchlist
+#[utoipa::path(
+    get,
+    path = "/api/alerts/watchlist/{id}/matches",
+    tag = "alerts",
+    summary = "Get matching marketplace items for a watchlist",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
 pub async fn get_watchlist_matches(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -290,3 +634,302 @@ pub async fn get_watchlist_matches(
         "count": result.len()
     })))
 }
+
+/// GET /api/alerts/watchlist/:id/triggers
+/// List the price drop / back in stock triggers of a watchlist
+#[utoipa::path(
+    get,
+    path = "/api/alerts/watchlist/{id}/triggers",
+    tag = "alerts",
+    summary = "List the price drop / back in stock triggers of a watchlist",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<WatchlistTrigger>),
+    )
+)]
+pub async fn get_watchlist_triggers(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(watchlist_id): Path<Uuid>,
+) -> Result<Json<Vec<WatchlistTrigger>>> {
+    let service = NotificationService::new(config.database_pool.clone());
+    Ok(Json(service.list_watchlist_triggers(watchlist_id, claims.user_id).await?))
+}
+
+/// POST /api/alerts/watchlist/:id/triggers
+/// Add a trigger on a watched NDC
+#[utoipa::path(
+    post,
+    path = "/api/alerts/watchlist/{id}/triggers",
+    tag = "alerts",
+    summary = "Add a trigger on a watched NDC",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    request_body = CreateWatchlistTriggerRequest,
+    responses(
+        (status = 200, description = "Success", body = WatchlistTrigger),
+    )
+)]
+pub async fn create_watchlist_trigger(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(watchlist_id): Path<Uuid>,
+    Json(request): Json<CreateWatchlistTriggerRequest>,
+) -> Result<Json<WatchlistTrigger>> {
+    tracing::info!(
+        "Creating {} trigger on watchlist {} for user: {}",
+        request.trigger_type.as_str(),
+        watchlist_id,
+        claims.user_id
+    );
+
+    let service = NotificationService::new(config.database_pool.clone());
+    Ok(Json(service.create_watchlist_trigger(watchlist_id, claims.user_id, request).await?))
+}
+
+/// PUT /api/alerts/watchlist/:id/triggers/:trigger_id
+#[utoipa::path(
+    put,
+    path = "/api/alerts/watchlist/{id}/triggers/{trigger_id}",
+    tag = "alerts",
+    params(
+        ("id" = Uuid, Path),
+        ("trigger_id" = Uuid, Path),
+    ),
+    request_body = UpdateWatchlistTriggerRequest,
+    responses(
+        (status = 200, description = "Success", body = WatchlistTrigger),
+    )
+)]
+pub async fn update_watchlist_trigger(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path((watchlist_id, trigger_id)): Path<(Uuid, Uuid)>,
+    Json(request): Json<UpdateWatchlistTriggerRequest>,
+) -> Result<Json<WatchlistTrigger>> {
+    let service = NotificationService::new(config.database_pool.clone());
+    Ok(Json(
+        service
+            .update_watchlist_trigger(watchlist_id, trigger_id, claims.user_id, request)
+            .await?,
+    ))
+}
+
+/// DELETE /api/alerts/watchlist/:id/triggers/:trigger_id
+#[utoipa::path(
+    delete,
+    path = "/api/alerts/watchlist/{id}/triggers/{trigger_id}",
+    tag = "alerts",
+    params(
+        ("id" = Uuid, Path),
+        ("trigger_id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
+pub async fn delete_watchlist_trigger(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path((watchlist_id, trigger_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<serde_json::Value>> {
+    let service = NotificationService::new(config.database_pool.clone());
+    service.delete_watchlist_trigger(watchlist_id, trigger_id, claims.user_id).await?;
+
+    Ok(Json(serde_json::json!({ "deleted": true })))
+}
+
+// ============================================================================
+// CHAT CHANNEL ENDPOINTS (Slack / Microsoft Teams)
+// ============================================================================
+
+/// GET /api/alerts/channels
+/// List connected chat channels (route alert types to them via /preferences)
+#[utoipa::path(
+    get,
+    path = "/api/alerts/channels",
+    tag = "alerts",
+    summary = "List connected chat channels (route alert types to them via /preferences)",
+    responses(
+        (status = 200, description = "Success", body = Vec<AlertChannel>),
+    )
+)]
+pub async fn get_channels(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Vec<AlertChannel>>> {
+    let service = AlertChannelService::new(config.database_pool.clone());
+    Ok(Json(service.list_channels(claims.user_id).await?))
+}
+
+/// POST /api/alerts/channels
+/// Connect a channel with an incoming webhook URL, or register a Slack OAuth app
+#[utoipa::path(
+    post,
+    path = "/api/alerts/channels",
+    tag = "alerts",
+    summary = "Connect a channel with an incoming webhook URL, or register a Slack OAuth app",
+    request_body = CreateAlertChannelRequest,
+    responses(
+        (status = 200, description = "Success", body = AlertChannel),
+    )
+)]
+pub async fn create_channel(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<CreateAlertChannelRequest>,
+) -> Result<Json<AlertChannel>> {
+    request.validate().map_err(AppError::Validation)?;
+
+    let service = AlertChannelService::new(config.database_pool.clone());
+    Ok(Json(service.create_channel(claims.user_id, request).await?))
+}
+
+/// GET /api/alerts/channels/:id
+#[utoipa::path(
+    get,
+    path = "/api/alerts/channels/{id}",
+    tag = "alerts",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = AlertChannel),
+    )
+)]
+pub async fn get_channel(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(channel_id): Path<Uuid>,
+) -> Result<Json<AlertChannel>> {
+    let service = AlertChannelService::new(config.database_pool.clone());
+    Ok(Json(service.get_channel(channel_id, claims.user_id).await?))
+}
+
+/// PUT /api/alerts/channels/:id
+#[utoipa::path(
+    put,
+    path = "/api/alerts/channels/{id}",
+    tag = "alerts",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    request_body = UpdateAlertChannelRequest,
+    responses(
+        (status = 200, description = "Success", body = AlertChannel),
+    )
+)]
+pub async fn update_channel(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(channel_id): Path<Uuid>,
+    Json(request): Json<UpdateAlertChannelRequest>,
+) -> Result<Json<AlertChannel>> {
+    request.validate().map_err(AppError::Validation)?;
+
+    let service = AlertChannelService::new(config.database_pool.clone());
+    Ok(Json(service.update_channel(channel_id, claims.user_id, request).await?))
+}
+
+/// DELETE /api/alerts/channels/:id
+/// Also removes the channel from the alert routes
+#[utoipa::path(
+    delete,
+    path = "/api/alerts/channels/{id}",
+    tag = "alerts",
+    summary = "Also removes the channel from the alert routes",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
+pub async fn delete_channel(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(channel_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>> {
+    let service = AlertChannelService::new(config.database_pool.clone());
+    service.delete_channel(channel_id, claims.user_id).await?;
+
+    Ok(Json(serde_json::json!({ "deleted": true })))
+}
+
+/// POST /api/alerts/channels/:id/authorize
+/// Start the Slack app install (OAuth channels)
+#[utoipa::path(
+    post,
+    path = "/api/alerts/channels/{id}/authorize",
+    tag = "alerts",
+    summary = "Start the Slack app install (OAuth channels)",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = AlertChannelAuthorizationResponse),
+    )
+)]
+pub async fn start_channel_authorization(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(channel_id): Path<Uuid>,
+) -> Result<Json<AlertChannelAuthorizationResponse>> {
+    let service = AlertChannelService::new(config.database_pool.clone());
+    let channel = service.get_channel(channel_id, claims.user_id).await?;
+
+    Ok(Json(service.start_authorization(&channel).await?))
+}
+
+/// POST /api/alerts/channels/:id/callback
+/// Finish the install with the code and state Slack redirected back with
+#[utoipa::path(
+    post,
+    path = "/api/alerts/channels/{id}/callback",
+    tag = "alerts",
+    summary = "Finish the install with the code and state Slack redirected back with",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    request_body = CompleteAlertChannelAuthorizationRequest,
+    responses(
+        (status = 200, description = "Success", body = AlertChannel),
+    )
+)]
+pub async fn complete_channel_authorization(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(channel_id): Path<Uuid>,
+    Json(request): Json<CompleteAlertChannelAuthorizationRequest>,
+) -> Result<Json<AlertChannel>> {
+    let service = AlertChannelService::new(config.database_pool.clone());
+    let channel = service.get_channel(channel_id, claims.user_id).await?;
+
+    Ok(Json(service.complete_authorization(&channel, &request).await?))
+}
+
+/// POST /api/alerts/channels/:id/test
+/// Post a sample alert to the channel
+#[utoipa::path(
+    post,
+    path = "/api/alerts/channels/{id}/test",
+    tag = "alerts",
+    summary = "Post a sample alert to the channel",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = AlertChannel),
+    )
+)]
+pub async fn test_channel(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(channel_id): Path<Uuid>,
+) -> Result<Json<AlertChannel>> {
+    let service = AlertChannelService::new(config.database_pool.clone());
+    Ok(Json(service.send_test(channel_id, claims.user_id).await?))
+}
@@ -0,0 +1,162 @@
+/// Platform Announcement REST API Handlers
+///
+/// Admins publish announcements targeted by role and country; users read them in the
+/// announcement feed (clients poll it with `since`) and mark them read.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use uuid::Uuid;
+use validator::Validate;
+use crate::{
+    config::AppConfig,
+    middleware::{error_handling::{AppError, Result}, Claims},
+    models::announcement::*,
+    services::AnnouncementService,
+};
+
+// ============================================================================
+// User Endpoints
+// ============================================================================
+
+/// GET /api/alerts/announcements
+/// Live announcements for the user, newest first, with read state
+#[utoipa::path(
+    get,
+    path = "/api/alerts/announcements",
+    tag = "announcements",
+    summary = "Live announcements for the user, newest first, with read state",
+    params(
+        AnnouncementFeedQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = AnnouncementFeed),
+    )
+)]
+pub async fn get_announcement_feed(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<AnnouncementFeedQuery>,
+) -> Result<Json<AnnouncementFeed>> {
+    let service = AnnouncementService::new(config.database_pool.clone());
+    Ok(Json(service.feed(claims.user_id, &query).await?))
+}
+
+/// POST /api/alerts/announcements/:id/read
+#[utoipa::path(
+    post,
+    path = "/api/alerts/announcements/{id}/read",
+    tag = "announcements",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
+pub async fn mark_announcement_read(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(announcement_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>> {
+    let service = AnnouncementService::new(config.database_pool.clone());
+    service.mark_read(claims.user_id, announcement_id).await?;
+
+    Ok(Json(serde_json::json!({ "success": true })))
+}
+
+// ============================================================================
+// Admin Endpoints
+// ============================================================================
+
+/// GET /api/admin/announcements
+/// All announcements (scheduled, live, expired, withdrawn) with read counts
+#[utoipa::path(
+    get,
+    path = "/api/admin/announcements",
+    tag = "announcements",
+    summary = "All announcements (scheduled, live, expired, withdrawn) with read counts",
+    params(
+        ListAnnouncementsQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<AnnouncementWithStats>),
+    )
+)]
+pub async fn list_announcements(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<ListAnnouncementsQuery>,
+) -> Result<Json<Vec<AnnouncementWithStats>>> {
+    crate::require_admin!(claims);
+
+    let limit = query.limit.unwrap_or(50).clamp(1, 200);
+    let offset = query.offset.unwrap_or(0).max(0);
+
+    let service = AnnouncementService::new(config.database_pool.clone());
+    Ok(Json(service.list(limit, offset).await?))
+}
+
+/// POST /api/admin/announcements
+/// Publish an announcement now, or at `publish_at`
+#[utoipa::path(
+    post,
+    path = "/api/admin/announcements",
+    tag = "announcements",
+    summary = "Publish an announcement now, or at `publish_at`",
+    request_body = CreateAnnouncementRequest,
+    responses(
+        (status = 201, description = "Created", body = Announcement),
+    )
+)]
+pub async fn create_announcement(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<CreateAnnouncementRequest>,
+) -> Result<(StatusCode, Json<Announcement>)> {
+    crate::require_admin!(claims);
+    request.validate().map_err(AppError::Validation)?;
+
+    let service = AnnouncementService::new(config.database_pool.clone());
+    let announcement = service.create(claims.user_id, request).await?;
+
+    tracing::info!(
+        "Audit: Admin {} created announcement {} ({} recipients)",
+        claims.user_id,
+        announcement.id,
+        announcement.recipient_count
+    );
+
+    Ok((StatusCode::CREATED, Json(announcement)))
+}
+
+/// POST /api/admin/announcements/:id/withdraw
+/// Remove an announcement from the feeds and dismiss its unread notifications
+#[utoipa::path(
+    post,
+    path = "/api/admin/announcements/{id}/withdraw",
+    tag = "announcements",
+    summary = "Remove an announcement from the feeds and dismiss its unread notifications",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = Announcement),
+    )
+)]
+pub async fn withdraw_announcement(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(announcement_id): Path<Uuid>,
+) -> Result<Json<Announcement>> {
+    crate::require_admin!(claims);
+
+    let service = AnnouncementService::new(config.database_pool.clone());
+    let announcement = service.withdraw(announcement_id).await?;
+
+    tracing::info!("Audit: Admin {} withdrew announcement {}", claims.user_id, announcement_id);
+
+    Ok(Json(announcement))
+}
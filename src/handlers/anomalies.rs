@@ -0,0 +1,156 @@
+/// Marketplace Anomaly REST API Handlers (admin only)
+///
+/// Findings of the scheduled anomaly detector (quantity jumps, off-market prices,
+/// duplicate listings) and their confirm/dismiss review workflow.
+
+use axum::{
+    extract::{Path, Query, State},
+    Extension, Json,
+};
+use uuid::Uuid;
+use validator::Validate;
+use crate::{
+    config::AppConfig,
+    middleware::{error_handling::{AppError, Result}, Claims},
+    models::anomaly::{Anomaly, AnomalyDetectionStats, AnomalyListResponse, ListAnomaliesQuery, ReviewAnomalyRequest},
+    services::AnomalyDetectionService,
+};
+
+/// GET /api/admin/anomalies
+/// Open findings by default (`?status=all` for every status), most severe first
+#[utoipa::path(
+    get,
+    path = "/api/admin/anomalies",
+    tag = "anomalies",
+    summary = "Open findings by default (`?status=all` for every status), most severe first",
+    params(
+        ListAnomaliesQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = AnomalyListResponse),
+    )
+)]
+pub async fn list_anomalies(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<ListAnomaliesQuery>,
+) -> Result<Json<AnomalyListResponse>> {
+    crate::require_admin!(claims);
+
+    let service = AnomalyDetectionService::new(config.database_pool.clone());
+    Ok(Json(service.list(&query).await?))
+}
+
+/// GET /api/admin/anomalies/:id
+#[utoipa::path(
+    get,
+    path = "/api/admin/anomalies/{id}",
+    tag = "anomalies",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = Anomaly),
+    )
+)]
+pub async fn get_anomaly(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(anomaly_id): Path<Uuid>,
+) -> Result<Json<Anomaly>> {
+    crate::require_admin!(claims);
+
+    let service = AnomalyDetectionService::new(config.database_pool.clone());
+    Ok(Json(service.get(anomaly_id).await?))
+}
+
+/// POST /api/admin/anomalies/:id/confirm
+#[utoipa::path(
+    post,
+    path = "/api/admin/anomalies/{id}/confirm",
+    tag = "anomalies",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    request_body = Option<ReviewAnomalyRequest>,
+    responses(
+        (status = 200, description = "Success", body = Anomaly),
+    )
+)]
+pub async fn confirm_anomaly(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(anomaly_id): Path<Uuid>,
+    request: Option<Json<ReviewAnomalyRequest>>,
+) -> Result<Json<Anomaly>> {
+    review_anomaly(config, claims, anomaly_id, "confirmed", request).await
+}
+
+/// POST /api/admin/anomalies/:id/dismiss
+#[utoipa::path(
+    post,
+    path = "/api/admin/anomalies/{id}/dismiss",
+    tag = "anomalies",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    request_body = Option<ReviewAnomalyRequest>,
+    responses(
+        (status = 200, description = "Success", body = Anomaly),
+    )
+)]
+pub async fn dismiss_anomaly(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(anomaly_id): Path<Uuid>,
+    request: Option<Json<ReviewAnomalyRequest>>,
+) -> Result<Json<Anomaly>> {
+    review_anomaly(config, claims, anomaly_id, "dismissed", request).await
+}
+
+async fn review_anomaly(
+    config: AppConfig,
+    claims: Claims,
+    anomaly_id: Uuid,
+    status: &str,
+    request: Option<Json<ReviewAnomalyRequest>>,
+) -> Result<Json<Anomaly>> {
+    crate::require_admin!(claims);
+
+    let request = request.map(|Json(r)| r).unwrap_or_default();
+    request.validate().map_err(AppError::Validation)?;
+
+    let service = AnomalyDetectionService::new(config.database_pool.clone());
+    let anomaly = service
+        .review(anomaly_id, status, request.notes.as_deref(), claims.user_id)
+        .await?;
+
+    tracing::info!("Audit: Admin {} marked anomaly {} as {}", claims.user_id, anomaly_id, status);
+
+    Ok(Json(anomaly))
+}
+
+/// POST /api/admin/anomalies/run
+/// Run the detector now instead of waiting for the next scheduled run
+#[utoipa::path(
+    post,
+    path = "/api/admin/anomalies/run",
+    tag = "anomalies",
+    summary = "Run the detector now instead of waiting for the next scheduled run",
+    responses(
+        (status = 200, description = "Success", body = AnomalyDetectionStats),
+    )
+)]
+pub async fn run_anomaly_detection(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<AnomalyDetectionStats>> {
+    crate::require_admin!(claims);
+
+    let service = AnomalyDetectionService::new(config.database_pool.clone());
+    let stats = service.run_detection().await?;
+
+    tracing::info!("Admin {} ran anomaly detection: {} new findings", claims.user_id, stats.total_new);
+
+    Ok(Json(stats))
+}
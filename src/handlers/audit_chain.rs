@@ -0,0 +1,96 @@
+/// Audit Log Chain REST API Handlers
+///
+/// Admin endpoints for the audit log hash chain: verify it end to end (or since an
+/// anchor), list anchors of the chain head and anchor the current head on demand.
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use crate::{
+    config::AppConfig,
+    middleware::{error_handling::Result, Claims},
+    models::audit_chain::*,
+    services::AuditChainService,
+};
+
+/// GET /api/admin/audit-logs/chain/verify
+/// Verify the whole chain, or from an anchor with `?since_anchor=<id>`
+#[utoipa::path(
+    get,
+    path = "/api/admin/audit-logs/chain/verify",
+    tag = "audit_chain",
+    summary = "Verify the whole chain, or from an anchor with `?since_anchor=<id>`",
+    params(
+        VerifyAuditChainQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = ChainVerificationReport),
+    )
+)]
+pub async fn verify_chain(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<VerifyAuditChainQuery>,
+) -> Result<Json<ChainVerificationReport>> {
+    crate::require_admin!(claims);
+
+    let service = AuditChainService::new(config.database_pool.clone());
+    let report = service.verify(query.since_anchor).await?;
+
+    tracing::info!(
+        "Audit: Admin {} verified audit chain {}..{}: {} issue(s)",
+        claims.user_id,
+        report.from_seq,
+        report.head_seq,
+        report.issue_count
+    );
+    Ok(Json(report))
+}
+
+/// GET /api/admin/audit-logs/chain/anchors
+#[utoipa::path(
+    get,
+    path = "/api/admin/audit-logs/chain/anchors",
+    tag = "audit_chain",
+    responses(
+        (status = 200, description = "Success", body = Vec<AuditChainAnchor>),
+    )
+)]
+pub async fn list_anchors(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Vec<AuditChainAnchor>>> {
+    crate::require_admin!(claims);
+
+    let service = AuditChainService::new(config.database_pool.clone());
+    Ok(Json(service.list_anchors(50).await?))
+}
+
+/// POST /api/admin/audit-logs/chain/anchors
+/// Anchor the current head; 204 when nothing was logged since the last anchor
+#[utoipa::path(
+    post,
+    path = "/api/admin/audit-logs/chain/anchors",
+    tag = "audit_chain",
+    summary = "Anchor the current head; 204 when nothing was logged since the last anchor",
+    responses(
+        (status = 204, description = "No content", body = Option<AuditChainAnchor>),
+    )
+)]
+pub async fn create_anchor(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+) -> Result<(StatusCode, Json<Option<AuditChainAnchor>>)> {
+    crate::require_admin!(claims);
+
+    let service = AuditChainService::new(config.database_pool.clone());
+    match service.anchor(Some(claims.user_id)).await? {
+        Some(anchor) => {
+            tracing::info!("Audit: Admin {} anchored audit chain at {}", claims.user_id, anchor.chain_seq);
+            Ok((StatusCode::CREATED, Json(Some(anchor))))
+        }
+        None => Ok((StatusCode::NO_CONTENT, Json(None))),
+    }
+}
@@ -40,6 +40,15 @@ fn create_logout_cookie() -> Cookie<'static> {
         .build()
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/auth/register",
+    tag = "auth",
+    request_body = CreateUserRequest,
+    responses(
+        (status = 200, description = "Registered; sets the auth_token cookie", body = UserResponse),
+    )
+)]
 pub async fn register(
     State(config): State<AppConfig>,
     Json(request): Json<CreateUserRequest>,
@@ -54,6 +63,25 @@ pub async fn register(
 
     let (user, token) = auth_service.register(request).await?;
 
+    // Screen the new company against the sanctions lists off the request path. An
+    // already registered email yields a placeholder user that doesn't exist; skip it.
+    let screening_pool = config.database_pool.clone();
+    let encryption_key = config.encryption_key.clone();
+    let user_id = user.id;
+    tokio::spawn(async move {
+        let result = match crate::services::SanctionsScreeningService::new(screening_pool, &encryption_key) {
+            Ok(service) => service
+                .screen_user(user_id, crate::models::sanctions::ScreeningTrigger::Registration, None)
+                .await
+                .map(|_| ()),
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(()) | Err(AppError::NotFound(_)) => {}
+            Err(e) => tracing::error!("Sanctions screening of new user {} failed: {}", user_id, e),
+        }
+    });
+
     // Check if TLS is enabled (production mode)
     let is_production = std::env::var("TLS_ENABLED")
         .unwrap_or_else(|_| "false".to_string())
@@ -75,6 +103,15 @@ pub async fn register(
     Ok(response)
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/auth/login",
+    tag = "auth",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "The user and token, or an MFA challenge; sets the auth_token cookie", body = serde_json::Value),
+    )
+)]
 pub async fn login(
     State(config): State<AppConfig>,
     Extension(audit): Extension<std::sync::Arc<crate::services::ComprehensiveAuditService>>,
@@ -176,6 +213,7 @@ pub async fn login(
             // 📋 AUDIT: Log failed login attempt
             let reason = match &e {
                 AppError::Unauthorized => "invalid_credentials",
+                AppError::Forbidden(_) => "account_suspended",
                 _ => "system_error",
             };
             let _ = audit.log_login_failed(
@@ -190,6 +228,14 @@ pub async fn login(
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/auth/profile",
+    tag = "auth",
+    responses(
+        (status = 200, description = "Success", body = UserResponse),
+    )
+)]
 pub async fn get_profile(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -203,6 +249,41 @@ pub async fn get_profile(
     Ok(Json(user))
 }
 
+/// GET /api/auth/limits
+/// The caller's request budgets and AI quotas, so integrations can slow down before a 429
+#[utoipa::path(
+    get,
+    path = "/api/auth/limits",
+    tag = "auth",
+    summary = "Current rate-limit budgets of the caller's IP and the account's AI quotas",
+    responses(
+        (status = 200, description = "Success", body = crate::models::rate_limit::RateLimitsResponse),
+    )
+)]
+pub async fn get_limits(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    axum::extract::ConnectInfo(addr): axum::extract::ConnectInfo<std::net::SocketAddr>,
+) -> Result<Json<crate::models::rate_limit::RateLimitsResponse>> {
+    let ai_quota = crate::services::AiQuotaService::new(config.database_pool.clone())
+        .get_usage(claims.user_id)
+        .await?;
+
+    Ok(Json(crate::models::rate_limit::RateLimitsResponse {
+        rate_limits: crate::middleware::ip_rate_limiter::RateLimiter::statuses(&addr.ip().to_string()),
+        ai_quota,
+    }))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/auth/profile",
+    tag = "auth",
+    request_body = crate::models::user::UpdateUserRequest,
+    responses(
+        (status = 200, description = "Success", body = UserResponse),
+    )
+)]
 pub async fn update_profile(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -220,6 +301,14 @@ pub async fn update_profile(
     Ok(Json(user))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/api/auth/delete",
+    tag = "auth",
+    responses(
+        (status = 204, description = "No content"),
+    )
+)]
 pub async fn delete_account(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -229,10 +318,22 @@ pub async fn delete_account(
         &config.jwt_secret,
     );
 
+    crate::services::LegalHoldService::new(config.database_pool.clone())
+        .ensure_user_deletable(claims.user_id, claims.user_id, "delete_account")
+        .await?;
+
     auth_service.delete_user(claims.user_id).await?;
     Ok(StatusCode::NO_CONTENT)
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/auth/refresh",
+    tag = "auth",
+    responses(
+        (status = 200, description = "Token refreshed; sets a new auth_token cookie"),
+    )
+)]
 pub async fn refresh_token(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -242,6 +343,10 @@ pub async fn refresh_token(
         &config.jwt_secret,
     );
 
+    crate::services::UserSuspensionService::new(config.database_pool.clone())
+        .ensure_not_suspended(claims.user_id)
+        .await?;
+
     let user = auth_service.get_user(claims.user_id).await?;
     let new_token = auth_service.generate_token(
         user.id,
@@ -269,6 +374,14 @@ pub async fn refresh_token(
     Ok(response)
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/auth/logout",
+    tag = "auth",
+    responses(
+        (status = 200, description = "Logged out; clears the auth_token cookie"),
+    )
+)]
 pub async fn logout(
     Extension(claims): Extension<Claims>,
     Extension(blacklist): Extension<std::sync::Arc<crate::services::TokenBlacklistService>>,
@@ -316,6 +429,15 @@ pub async fn logout(
 /// - PCI DSS Requirement 8.2.4 (Password change)
 /// - HIPAA §164.308(a)(5) (Access management)
 ///
+#[utoipa::path(
+    post,
+    path = "/api/auth/change-password",
+    tag = "auth",
+    request_body = serde_json::Value,
+    responses(
+        (status = 200, description = "Password changed; other sessions are revoked and a new token is issued", body = serde_json::Value),
+    )
+)]
 pub async fn change_password(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -0,0 +1,123 @@
+/// Background Job REST API Handlers
+///
+/// Admin endpoints for the persistent background job queue: list jobs by type and status,
+/// inspect one, queue a failed or cancelled job again and cancel a queued or running job.
+
+use axum::{
+    extract::{Path, Query, State},
+    Extension, Json,
+};
+use uuid::Uuid;
+use crate::{
+    config::AppConfig,
+    middleware::{error_handling::Result, Claims},
+    models::background_job::*,
+    services::BackgroundJobService,
+};
+
+/// GET /api/admin/jobs
+/// Newest first; filter with `?job_type=`, `?status=`, page with `?limit=&offset=`
+#[utoipa::path(
+    get,
+    path = "/api/admin/jobs",
+    tag = "background_jobs",
+    summary = "Newest first; filter with `?job_type=`, `?status=`, page with `?limit=&offset=`",
+    params(
+        BackgroundJobQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<BackgroundJob>),
+    )
+)]
+pub async fn list_jobs(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<BackgroundJobQuery>,
+) -> Result<Json<Vec<BackgroundJob>>> {
+    crate::require_admin!(claims);
+
+    let service = BackgroundJobService::new(config.database_pool.clone());
+    Ok(Json(service.list(&query).await?))
+}
+
+/// GET /api/admin/jobs/:id
+#[utoipa::path(
+    get,
+    path = "/api/admin/jobs/{id}",
+    tag = "background_jobs",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = BackgroundJob),
+    )
+)]
+pub async fn get_job(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(job_id): Path<Uuid>,
+) -> Result<Json<BackgroundJob>> {
+    crate::require_admin!(claims);
+
+    let service = BackgroundJobService::new(config.database_pool.clone());
+    Ok(Json(service.get(job_id).await?))
+}
+
+/// POST /api/admin/jobs/:id/retry
+/// Queue a failed or cancelled job again with one more attempt, optionally at `run_at`
+#[utoipa::path(
+    post,
+    path = "/api/admin/jobs/{id}/retry",
+    tag = "background_jobs",
+    summary = "Queue a failed or cancelled job again with one more attempt, optionally at `run_at`",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    request_body = Option<RetryBackgroundJobRequest>,
+    responses(
+        (status = 200, description = "Success", body = BackgroundJob),
+    )
+)]
+pub async fn retry_job(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(job_id): Path<Uuid>,
+    request: Option<Json<RetryBackgroundJobRequest>>,
+) -> Result<Json<BackgroundJob>> {
+    crate::require_admin!(claims);
+
+    let run_at = request.and_then(|Json(request)| request.run_at);
+    let service = BackgroundJobService::new(config.database_pool.clone());
+    let job = service.retry(job_id, run_at).await?;
+
+    tracing::info!("Audit: Admin {} retried {} job {}", claims.user_id, job.job_type, job.id);
+    Ok(Json(job))
+}
+
+/// POST /api/admin/jobs/:id/cancel
+/// Queued jobs are cancelled right away; running jobs finish their attempt and are not retried
+#[utoipa::path(
+    post,
+    path = "/api/admin/jobs/{id}/cancel",
+    tag = "background_jobs",
+    summary = "Queued jobs are cancelled right away; running jobs finish their attempt and are not retried",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = BackgroundJob),
+    )
+)]
+pub async fn cancel_job(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(job_id): Path<Uuid>,
+) -> Result<Json<BackgroundJob>> {
+    crate::require_admin!(claims);
+
+    let service = BackgroundJobService::new(config.database_pool.clone());
+    let job = service.cancel(job_id).await?;
+
+    tracing::info!("Audit: Admin {} cancelled {} job {} ({})", claims.user_id, job.job_type, job.id, job.status);
+    Ok(Json(job))
+}
@@ -0,0 +1,114 @@
+/// Batch Operation REST API Handlers
+///
+/// A user submits up to 100 operations of one kind (create inquiries, update inventory,
+/// dismiss notifications) in one request. A background job runs them, each committing on
+/// its own; the client polls the batch and reads the result of every operation.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use uuid::Uuid;
+use crate::{
+    config::AppConfig,
+    middleware::{error_handling::Result, Claims},
+    models::{background_job::{BatchOperationJob, JobType, NewJob}, batch_operation::*},
+    services::{BackgroundJobService, BatchOperationService},
+};
+
+/// POST /api/batch
+/// Queue up to 100 operations of one kind; a background job runs them one by one
+#[utoipa::path(
+    post,
+    path = "/api/batch",
+    tag = "batch_operations",
+    summary = "Queue up to 100 operations of one kind; a background job runs them one by one",
+    request_body = CreateBatchOperationRequest,
+    responses(
+        (status = 202, description = "Accepted", body = BatchOperation),
+    )
+)]
+pub async fn create_batch_operation(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<CreateBatchOperationRequest>,
+) -> Result<(StatusCode, Json<BatchOperation>)> {
+    let service = BatchOperationService::new(config.database_pool.clone(), &config.encryption_key);
+    let batch = service.create(&request, claims.user_id).await?;
+
+    tracing::info!(
+        "Audit: User {} submitted {} batch {} with {} operations",
+        claims.user_id,
+        batch.operation,
+        batch.id,
+        batch.total_items
+    );
+
+    let job = BatchOperationJob { batch_id: batch.id };
+    let queued = BackgroundJobService::new(config.database_pool.clone())
+        .enqueue(
+            NewJob::new(JobType::BatchOperation, &job)
+                .with_dedupe_key(format!("batch_operation:{}", batch.id))
+                .created_by(claims.user_id),
+        )
+        .await;
+    let job = match queued {
+        Ok(job) => job,
+        Err(e) => {
+            service.fail(batch.id, "Batch could not be queued").await?;
+            return Err(e);
+        }
+    };
+
+    let batch = service.set_job(batch.id, job.id).await?;
+    Ok((StatusCode::ACCEPTED, Json(batch)))
+}
+
+/// GET /api/batch/:id
+/// Status and progress of a batch
+#[utoipa::path(
+    get,
+    path = "/api/batch/{id}",
+    tag = "batch_operations",
+    summary = "Status and progress of a batch",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = BatchOperation),
+    )
+)]
+pub async fn get_batch_operation(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(batch_id): Path<Uuid>,
+) -> Result<Json<BatchOperation>> {
+    let service = BatchOperationService::new(config.database_pool.clone(), &config.encryption_key);
+    Ok(Json(service.get(batch_id, claims.user_id).await?))
+}
+
+/// GET /api/batch/:id/results
+/// Result or error of every operation so far, in submission order (partial while running)
+#[utoipa::path(
+    get,
+    path = "/api/batch/{id}/results",
+    tag = "batch_operations",
+    summary = "Result or error of every operation so far, in submission order (partial while running)",
+    params(
+        ("id" = Uuid, Path),
+        BatchOperationResultsQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = BatchOperationResults),
+    )
+)]
+pub async fn get_batch_operation_results(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(batch_id): Path<Uuid>,
+    Query(query): Query<BatchOperationResultsQuery>,
+) -> Result<Json<BatchOperationResults>> {
+    let service = BatchOperationService::new(config.database_pool.clone(), &config.encryption_key);
+    Ok(Json(service.results(batch_id, claims.user_id, query.status.as_deref()).await?))
+}
@@ -0,0 +1,248 @@
+/// REST API handlers for usage metering, billing plans and monthly statements
+
+use axum::{
+    extract::{Path, Query, State},
+    Extension,
+    Json,
+};
+use chrono::{NaiveDate, Utc};
+use uuid::Uuid;
+use crate::{
+    config::AppConfig,
+    middleware::{error_handling::{Result, AppError}, Claims},
+    models::billing::*,
+    services::{
+        comprehensive_audit_service::{ActionResult, AuditLogEntry, ComprehensiveAuditService, EventCategory, Severity},
+        UsageMeteringService,
+    },
+};
+
+/// `?period=YYYY-MM`, defaulting to the current month
+fn parse_period(period: Option<&str>) -> Result<NaiveDate> {
+    match period {
+        Some(period) => parse_billing_month(period)
+            .ok_or_else(|| AppError::BadRequest(format!("Invalid billing period '{}', expected YYYY-MM", period))),
+        None => Ok(billing_period(Utc::now().date_naive()).0),
+    }
+}
+
+// ============================================================================
+// User Endpoints
+// ============================================================================
+
+/// GET /api/billing/usage
+/// Metered usage and charges so far for `?period=YYYY-MM` (default: current month)
+#[utoipa::path(
+    get,
+    path = "/api/billing/usage",
+    tag = "billing",
+    operation_id = "billing_get_usage",
+    summary = "Metered usage and charges so far for `?period=YYYY-MM` (default: current month)",
+    params(
+        BillingUsageQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = UsageReport),
+    )
+)]
+pub async fn get_usage(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<BillingUsageQuery>,
+) -> Result<Json<UsageReport>> {
+    let period_start = parse_period(query.period.as_deref())?;
+    let service = UsageMeteringService::new(config.database_pool.clone());
+    Ok(Json(service.usage(claims.user_id, period_start).await?))
+}
+
+/// GET /api/billing/account
+/// The account's billing plan
+#[utoipa::path(
+    get,
+    path = "/api/billing/account",
+    tag = "billing",
+    summary = "The account's billing plan",
+    responses(
+        (status = 200, description = "Success", body = BillingAccount),
+    )
+)]
+pub async fn get_account(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<BillingAccount>> {
+    let service = UsageMeteringService::new(config.database_pool.clone());
+    Ok(Json(service.account(claims.user_id).await?))
+}
+
+/// GET /api/billing/plans
+/// Plans on offer with their included quantities and overage prices
+#[utoipa::path(
+    get,
+    path = "/api/billing/plans",
+    tag = "billing",
+    summary = "Plans on offer with their included quantities and overage prices",
+    responses(
+        (status = 200, description = "Success", body = Vec<BillingPlanDetails>),
+    )
+)]
+pub async fn list_plans(
+    State(config): State<AppConfig>,
+) -> Result<Json<Vec<BillingPlanDetails>>> {
+    let service = UsageMeteringService::new(config.database_pool.clone());
+    Ok(Json(service.list_plans().await?))
+}
+
+/// GET /api/billing/statements
+/// Monthly statements, newest first
+#[utoipa::path(
+    get,
+    path = "/api/billing/statements",
+    tag = "billing",
+    summary = "Monthly statements, newest first",
+    responses(
+        (status = 200, description = "Success", body = Vec<BillingStatement>),
+    )
+)]
+pub async fn list_statements(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Vec<BillingStatement>>> {
+    let service = UsageMeteringService::new(config.database_pool.clone());
+    Ok(Json(service.list_statements(claims.user_id).await?))
+}
+
+/// GET /api/billing/statements/:id
+#[utoipa::path(
+    get,
+    path = "/api/billing/statements/{id}",
+    tag = "billing",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = BillingStatement),
+    )
+)]
+pub async fn get_statement(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(statement_id): Path<Uuid>,
+) -> Result<Json<BillingStatement>> {
+    let service = UsageMeteringService::new(config.database_pool.clone());
+    Ok(Json(service.get_statement(claims.user_id, statement_id).await?))
+}
+
+// ============================================================================
+// Admin Endpoints
+// ============================================================================
+
+/// GET /api/admin/billing/accounts/:user_id/usage
+/// An account's metered usage for `?period=YYYY-MM`
+#[utoipa::path(
+    get,
+    path = "/api/admin/billing/accounts/{user_id}/usage",
+    tag = "billing",
+    operation_id = "billing_admin_get_usage",
+    summary = "An account's metered usage for `?period=YYYY-MM`",
+    params(
+        ("user_id" = Uuid, Path),
+        BillingUsageQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = UsageReport),
+    )
+)]
+pub async fn admin_get_usage(
+    State(config): State<AppConfig>,
+    Path(user_id): Path<Uuid>,
+    Query(query): Query<BillingUsageQuery>,
+) -> Result<Json<UsageReport>> {
+    let period_start = parse_period(query.period.as_deref())?;
+    let service = UsageMeteringService::new(config.database_pool.clone());
+    Ok(Json(service.usage(user_id, period_start).await?))
+}
+
+/// PUT /api/admin/billing/accounts/:user_id/plan
+/// Move an account to another billing plan (superadmin)
+#[utoipa::path(
+    put,
+    path = "/api/admin/billing/accounts/{user_id}/plan",
+    tag = "billing",
+    summary = "Move an account to another billing plan (superadmin)",
+    params(
+        ("user_id" = Uuid, Path),
+    ),
+    request_body = AssignBillingPlanRequest,
+    responses(
+        (status = 200, description = "Success", body = BillingAccount),
+    )
+)]
+pub async fn admin_assign_plan(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(user_id): Path<Uuid>,
+    Json(request): Json<AssignBillingPlanRequest>,
+) -> Result<Json<BillingAccount>> {
+    let service = UsageMeteringService::new(config.database_pool.clone());
+    let previous = service.account(user_id).await?;
+    let account = service.assign_plan(user_id, &request, claims.user_id).await?;
+
+    ComprehensiveAuditService::new(config.database_pool.clone()).log(AuditLogEntry {
+        event_type: "admin_billing_plan_update".to_string(),
+        event_category: EventCategory::Admin,
+        severity: Severity::Warning,
+        actor_user_id: Some(claims.user_id),
+        actor_type: "user".to_string(),
+        resource_type: Some("billing_account".to_string()),
+        resource_id: Some(user_id.to_string()),
+        action: "assign_billing_plan".to_string(),
+        action_result: ActionResult::Success,
+        event_data: serde_json::json!({
+            "user_id": user_id,
+            "previous_plan": previous.plan,
+            "new_plan": account.plan,
+        }),
+        ip_address: None,
+        is_pii_access: false,
+        compliance_tags: vec!["admin".to_string(), "billing".to_string()],
+        ..Default::default()
+    }).await?;
+
+    Ok(Json(account))
+}
+
+/// POST /api/admin/billing/statements/generate
+/// Generate the statements of a month that has ended (superadmin). Existing statements
+/// are kept unless `regenerate` is set.
+#[utoipa::path(
+    post,
+    path = "/api/admin/billing/statements/generate",
+    tag = "billing",
+    summary = "Generate the statements of a month that has ended (superadmin). Existing statements",
+    request_body = GenerateStatementsRequest,
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
+pub async fn admin_generate_statements(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<GenerateStatementsRequest>,
+) -> Result<Json<serde_json::Value>> {
+    let period_start = parse_period(Some(&request.period))?;
+    let service = UsageMeteringService::new(config.database_pool.clone());
+    let generated = service.generate_statements(period_start, request.regenerate).await?;
+
+    tracing::info!(
+        "Audit: Admin {} generated {} billing statements for {} (regenerate: {})",
+        claims.user_id,
+        generated,
+        request.period,
+        request.regenerate
+    );
+
+    Ok(Json(serde_json::json!({
+        "period": request.period,
+        "generated": generated,
+    })))
+}
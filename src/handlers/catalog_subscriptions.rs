@@ -0,0 +1,170 @@
+/// Catalog Change-Subscription REST API Handlers
+///
+/// Subscribe to status changes on specific OpenFDA/EMA catalog records.
+
+use axum::{
+    extract::{Path, Query, State},
+    Extension, Json,
+};
+use uuid::Uuid;
+use crate::{
+    config::AppConfig,
+    middleware::{error_handling::Result, Claims},
+    models::catalog_subscription::*,
+    services::CatalogSubscriptionService,
+};
+
+/// GET /api/catalog-subscriptions
+/// List the current user's subscriptions
+#[utoipa::path(
+    get,
+    path = "/api/catalog-subscriptions",
+    tag = "catalog_subscriptions",
+    summary = "List the current user's subscriptions",
+    responses(
+        (status = 200, description = "Success", body = Vec<CatalogSubscription>),
+    )
+)]
+pub async fn list_subscriptions(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Vec<CatalogSubscription>>> {
+    let service = CatalogSubscriptionService::new(config.database_pool.clone());
+    let subscriptions = service.list_subscriptions(claims.user_id).await?;
+
+    Ok(Json(subscriptions))
+}
+
+/// POST /api/catalog-subscriptions
+/// Subscribe to an NDC or EU number (the webhook secret is only returned here)
+#[utoipa::path(
+    post,
+    path = "/api/catalog-subscriptions",
+    tag = "catalog_subscriptions",
+    summary = "Subscribe to an NDC or EU number (the webhook secret is only returned here)",
+    request_body = CreateCatalogSubscriptionRequest,
+    responses(
+        (status = 200, description = "Success", body = CreatedCatalogSubscriptionResponse),
+    )
+)]
+pub async fn create_subscription(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<CreateCatalogSubscriptionRequest>,
+) -> Result<Json<CreatedCatalogSubscriptionResponse>> {
+    let service = CatalogSubscriptionService::new(config.database_pool.clone());
+    let created = service.create_subscription(claims.user_id, request).await?;
+
+    Ok(Json(created))
+}
+
+/// GET /api/catalog-subscriptions/:id
+/// Get a single subscription
+#[utoipa::path(
+    get,
+    path = "/api/catalog-subscriptions/{id}",
+    tag = "catalog_subscriptions",
+    summary = "Get a single subscription",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = CatalogSubscription),
+    )
+)]
+pub async fn get_subscription(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(subscription_id): Path<Uuid>,
+) -> Result<Json<CatalogSubscription>> {
+    let service = CatalogSubscriptionService::new(config.database_pool.clone());
+    let subscription = service.get_subscription(subscription_id, claims.user_id).await?;
+
+    Ok(Json(subscription))
+}
+
+/// PUT /api/catalog-subscriptions/:id
+/// Update change types, delivery channels or active state
+#[utoipa::path(
+    put,
+    path = "/api/catalog-subscriptions/{id}",
+    tag = "catalog_subscriptions",
+    summary = "Update change types, delivery channels or active state",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    request_body = UpdateCatalogSubscriptionRequest,
+    responses(
+        (status = 200, description = "Success", body = CatalogSubscription),
+    )
+)]
+pub async fn update_subscription(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(subscription_id): Path<Uuid>,
+    Json(request): Json<UpdateCatalogSubscriptionRequest>,
+) -> Result<Json<CatalogSubscription>> {
+    let service = CatalogSubscriptionService::new(config.database_pool.clone());
+    let subscription = service
+        .update_subscription(subscription_id, claims.user_id, request)
+        .await?;
+
+    Ok(Json(subscription))
+}
+
+/// DELETE /api/catalog-subscriptions/:id
+/// Remove a subscription
+#[utoipa::path(
+    delete,
+    path = "/api/catalog-subscriptions/{id}",
+    tag = "catalog_subscriptions",
+    summary = "Remove a subscription",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
+pub async fn delete_subscription(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(subscription_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>> {
+    let service = CatalogSubscriptionService::new(config.database_pool.clone());
+    service.delete_subscription(subscription_id, claims.user_id).await?;
+
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "message": "Subscription deleted"
+    })))
+}
+
+/// GET /api/catalog-subscriptions/:id/events
+/// Change history of the subscribed record
+#[utoipa::path(
+    get,
+    path = "/api/catalog-subscriptions/{id}/events",
+    tag = "catalog_subscriptions",
+    summary = "Change history of the subscribed record",
+    params(
+        ("id" = Uuid, Path),
+        CatalogChangeEventsQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<CatalogChangeEvent>),
+    )
+)]
+pub async fn get_subscription_events(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(subscription_id): Path<Uuid>,
+    Query(query): Query<CatalogChangeEventsQuery>,
+) -> Result<Json<Vec<CatalogChangeEvent>>> {
+    let service = CatalogSubscriptionService::new(config.database_pool.clone());
+    let events = service
+        .get_subscription_events(subscription_id, claims.user_id, query)
+        .await?;
+
+    Ok(Json(events))
+}
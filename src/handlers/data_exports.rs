@@ -0,0 +1,167 @@
+/// Data Export REST API Handlers
+///
+/// A user queues an export (inventory, transactions, audit logs, OpenFDA or EMA catalog),
+/// polls it for progress and downloads the finished file through the signed link the
+/// export carries once completed. The download itself needs no session, so links work
+/// from download managers; they expire after a few minutes.
+
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, HeaderValue, StatusCode},
+    response::Response,
+    Extension, Json,
+};
+use futures::stream;
+use tokio::io::AsyncReadExt;
+use uuid::Uuid;
+use crate::{
+    config::AppConfig,
+    middleware::{error_handling::Result, Claims},
+    models::{background_job::{DataExportJob, JobType, NewJob}, data_export::*},
+    services::{BackgroundJobService, DataExportService},
+};
+
+/// Bytes read from the export file per response chunk
+const DOWNLOAD_CHUNK_BYTES: usize = 64 * 1024;
+
+fn service(config: &AppConfig) -> DataExportService {
+    DataExportService::new(config.database_pool.clone(), &config.file_storage_path, &config.encryption_key)
+}
+
+/// POST /api/exports
+/// Queue an export; a background job writes its file
+#[utoipa::path(
+    post,
+    path = "/api/exports",
+    tag = "data_exports",
+    summary = "Queue an export; a background job writes its file",
+    request_body = CreateDataExportRequest,
+    responses(
+        (status = 202, description = "Accepted", body = DataExportResponse),
+    )
+)]
+pub async fn create_export(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<CreateDataExportRequest>,
+) -> Result<(StatusCode, Json<DataExportResponse>)> {
+    let service = service(&config);
+    let export = service.create(request, &claims).await?;
+
+    tracing::info!(
+        "Audit: User {} started {} export {} ({})",
+        claims.user_id,
+        export.export_type,
+        export.id,
+        export.format
+    );
+
+    let job = DataExportJob { export_id: export.id };
+    let queued = BackgroundJobService::new(config.database_pool.clone())
+        .enqueue(
+            NewJob::new(JobType::DataExport, &job)
+                .with_dedupe_key(format!("data_export:{}", export.id))
+                .created_by(claims.user_id),
+        )
+        .await;
+    if let Err(e) = queued {
+        service.fail(export.id, "Export could not be queued").await?;
+        return Err(e);
+    }
+
+    Ok((StatusCode::ACCEPTED, Json(service.response(export))))
+}
+
+/// GET /api/exports
+#[utoipa::path(
+    get,
+    path = "/api/exports",
+    tag = "data_exports",
+    params(
+        DataExportQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<DataExportResponse>),
+    )
+)]
+pub async fn list_exports(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<DataExportQuery>,
+) -> Result<Json<Vec<DataExportResponse>>> {
+    let service = service(&config);
+    let exports = service.list(claims.user_id, query.limit.unwrap_or(20)).await?;
+    Ok(Json(exports.into_iter().map(|export| service.response(export)).collect()))
+}
+
+/// GET /api/exports/:id
+/// Progress; completed exports carry a freshly signed download link
+#[utoipa::path(
+    get,
+    path = "/api/exports/{id}",
+    tag = "data_exports",
+    summary = "Progress; completed exports carry a freshly signed download link",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = DataExportResponse),
+    )
+)]
+pub async fn get_export(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(export_id): Path<Uuid>,
+) -> Result<Json<DataExportResponse>> {
+    let service = service(&config);
+    let export = service.get(export_id, claims.user_id).await?;
+    Ok(Json(service.response(export)))
+}
+
+/// GET /api/exports/:id/download?expires=..&signature=..
+/// Stream the export file (public: authorized by the link's signature)
+#[utoipa::path(
+    get,
+    path = "/api/exports/{id}/download",
+    tag = "data_exports",
+    summary = "Stream the export file (public: authorized by the link's signature)",
+    params(
+        ("id" = Uuid, Path),
+        DataExportDownloadQuery,
+    ),
+    responses(
+        (status = 200, description = "The export file", body = crate::openapi::FileDownload, content_type = "application/octet-stream"),
+    )
+)]
+pub async fn download_export(
+    State(config): State<AppConfig>,
+    Path(export_id): Path<Uuid>,
+    Query(query): Query<DataExportDownloadQuery>,
+) -> Result<Response> {
+    let (export, file) = service(&config).open_download(export_id, &query).await?;
+
+    let chunks = stream::try_unfold(file, |mut file| async move {
+        let mut buffer = vec![0; DOWNLOAD_CHUNK_BYTES];
+        let read = file.read(&mut buffer).await?;
+        if read == 0 {
+            return Ok::<_, std::io::Error>(None);
+        }
+        buffer.truncate(read);
+        Ok(Some((buffer, file)))
+    });
+
+    let mut response = Response::new(Body::from_stream(chunks));
+    let headers = response.headers_mut();
+
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(export.export_format().content_type()));
+    if let Ok(value) = HeaderValue::from_str(&format!("attachment; filename=\"{}\"", export.filename())) {
+        headers.insert(header::CONTENT_DISPOSITION, value);
+    }
+    if let Some(size) = export.file_size_bytes {
+        headers.insert(header::CONTENT_LENGTH, HeaderValue::from(size));
+    }
+    headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("private, no-store"));
+
+    Ok(response)
+}
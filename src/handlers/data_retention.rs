@@ -0,0 +1,135 @@
+/// Data Retention REST API Handlers (superadmin only)
+///
+/// Retention periods per data category and purge runs with their per-category reports.
+/// Legal holds are managed under /api/admin/legal-holds.
+
+use axum::{
+    extract::{Path, Query, State},
+    Extension, Json,
+};
+use uuid::Uuid;
+use crate::{
+    config::AppConfig,
+    middleware::{error_handling::Result, Claims},
+    models::data_retention::*,
+    services::DataRetentionService,
+};
+
+/// GET /api/admin/retention/policies
+#[utoipa::path(
+    get,
+    path = "/api/admin/retention/policies",
+    tag = "data_retention",
+    responses(
+        (status = 200, description = "Success", body = Vec<DataRetentionPolicy>),
+    )
+)]
+pub async fn list_policies(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Vec<DataRetentionPolicy>>> {
+    crate::require_superadmin!(claims);
+
+    let service = DataRetentionService::new(config.database_pool.clone());
+    Ok(Json(service.list_policies().await?))
+}
+
+/// PUT /api/admin/retention/policies/:category
+/// Set the retention period of a category or enable/disable its purge
+#[utoipa::path(
+    put,
+    path = "/api/admin/retention/policies/{category}",
+    tag = "data_retention",
+    summary = "Set the retention period of a category or enable/disable its purge",
+    params(
+        ("category" = String, Path),
+    ),
+    request_body = UpdateRetentionPolicyRequest,
+    responses(
+        (status = 200, description = "Success", body = DataRetentionPolicy),
+    )
+)]
+pub async fn update_policy(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(category): Path<String>,
+    Json(request): Json<UpdateRetentionPolicyRequest>,
+) -> Result<Json<DataRetentionPolicy>> {
+    crate::require_superadmin!(claims);
+
+    let service = DataRetentionService::new(config.database_pool.clone());
+    let policy = service.update_policy(&category, request, claims.user_id).await?;
+    Ok(Json(policy))
+}
+
+/// GET /api/admin/retention/runs
+#[utoipa::path(
+    get,
+    path = "/api/admin/retention/runs",
+    tag = "data_retention",
+    params(
+        PurgeRunQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<DataPurgeRun>),
+    )
+)]
+pub async fn list_purge_runs(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<PurgeRunQuery>,
+) -> Result<Json<Vec<DataPurgeRun>>> {
+    crate::require_superadmin!(claims);
+
+    let limit = query.limit.unwrap_or(20).clamp(1, 100);
+    let offset = query.offset.unwrap_or(0).max(0);
+
+    let service = DataRetentionService::new(config.database_pool.clone());
+    Ok(Json(service.list_runs(limit, offset).await?))
+}
+
+/// GET /api/admin/retention/runs/:id
+/// A run with what it purged per category
+#[utoipa::path(
+    get,
+    path = "/api/admin/retention/runs/{id}",
+    tag = "data_retention",
+    summary = "A run with what it purged per category",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = DataPurgeRun),
+    )
+)]
+pub async fn get_purge_run(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(run_id): Path<Uuid>,
+) -> Result<Json<DataPurgeRun>> {
+    crate::require_superadmin!(claims);
+
+    let service = DataRetentionService::new(config.database_pool.clone());
+    Ok(Json(service.get_run(run_id).await?))
+}
+
+/// POST /api/admin/retention/runs
+/// Purge now instead of waiting for the daily run
+#[utoipa::path(
+    post,
+    path = "/api/admin/retention/runs",
+    tag = "data_retention",
+    summary = "Purge now instead of waiting for the daily run",
+    responses(
+        (status = 200, description = "Success", body = DataPurgeRun),
+    )
+)]
+pub async fn start_purge_run(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<DataPurgeRun>> {
+    crate::require_superadmin!(claims);
+
+    let service = DataRetentionService::new(config.database_pool.clone());
+    Ok(Json(service.run_purge(Some(claims.user_id)).await?))
+}
@@ -0,0 +1,144 @@
+/// Regulatory Document Batch REST API Handlers
+///
+/// A seller generates one document type (DSCSA transaction data, CoA, GDP or GMP) for a
+/// filtered set of their transactions in a background job, follows its progress and
+/// downloads the generated documents as a ZIP.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderValue, StatusCode},
+    response::Response,
+    Extension, Json,
+};
+use uuid::Uuid;
+use validator::Validate;
+use crate::{
+    config::AppConfig,
+    middleware::{error_handling::{AppError, Result}, Claims},
+    models::{background_job::{DocumentBatchJob, JobType, NewJob}, document_batch::*},
+    services::{BackgroundJobService, DocumentBatchService},
+};
+
+/// POST /api/regulatory/documents/batches
+/// Queue a batch for the matching transactions; a background job generates its documents
+#[utoipa::path(
+    post,
+    path = "/api/regulatory/documents/batches",
+    tag = "document_batches",
+    summary = "Queue a batch for the matching transactions; a background job generates its documents",
+    request_body = CreateDocumentBatchRequest,
+    responses(
+        (status = 202, description = "Accepted", body = DocumentBatch),
+    )
+)]
+pub async fn create_batch(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<CreateDocumentBatchRequest>,
+) -> Result<(StatusCode, Json<DocumentBatch>)> {
+    request.validate().map_err(AppError::Validation)?;
+
+    let service = DocumentBatchService::new(config.database_pool.clone(), &config.encryption_key);
+    let batch = service.create(request, claims.user_id).await?;
+
+    tracing::info!(
+        "Audit: User {} started {} document batch {} for {} transactions",
+        claims.user_id,
+        batch.document_type,
+        batch.id,
+        batch.total_items
+    );
+
+    let job = DocumentBatchJob { batch_id: batch.id };
+    let queued = BackgroundJobService::new(config.database_pool.clone())
+        .enqueue(
+            NewJob::new(JobType::DocumentBatch, &job)
+                .with_dedupe_key(format!("document_batch:{}", batch.id))
+                .created_by(claims.user_id),
+        )
+        .await;
+    if let Err(e) = queued {
+        service.fail(batch.id, "Batch could not be queued").await?;
+        return Err(e);
+    }
+
+    Ok((StatusCode::ACCEPTED, Json(batch)))
+}
+
+/// GET /api/regulatory/documents/batches
+#[utoipa::path(
+    get,
+    path = "/api/regulatory/documents/batches",
+    tag = "document_batches",
+    params(
+        DocumentBatchQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<DocumentBatch>),
+    )
+)]
+pub async fn list_batches(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<DocumentBatchQuery>,
+) -> Result<Json<Vec<DocumentBatch>>> {
+    let service = DocumentBatchService::new(config.database_pool.clone(), &config.encryption_key);
+    Ok(Json(service.list(claims.user_id, query.limit.unwrap_or(20)).await?))
+}
+
+/// GET /api/regulatory/documents/batches/:id
+/// Progress and per-transaction results
+#[utoipa::path(
+    get,
+    path = "/api/regulatory/documents/batches/{id}",
+    tag = "document_batches",
+    summary = "Progress and per-transaction results",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = DocumentBatchDetail),
+    )
+)]
+pub async fn get_batch(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(batch_id): Path<Uuid>,
+) -> Result<Json<DocumentBatchDetail>> {
+    let service = DocumentBatchService::new(config.database_pool.clone(), &config.encryption_key);
+    Ok(Json(service.get(batch_id, claims.user_id).await?))
+}
+
+/// GET /api/regulatory/documents/batches/:id/download
+/// ZIP of the generated documents with a manifest of the results (finished batches)
+#[utoipa::path(
+    get,
+    path = "/api/regulatory/documents/batches/{id}/download",
+    tag = "document_batches",
+    summary = "ZIP of the generated documents with a manifest of the results (finished batches)",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "The batch archive", body = crate::openapi::FileDownload, content_type = "application/zip"),
+    )
+)]
+pub async fn download_batch(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(batch_id): Path<Uuid>,
+) -> Result<Response> {
+    let service = DocumentBatchService::new(config.database_pool.clone(), &config.encryption_key);
+    let (filename, data) = service.archive(batch_id, claims.user_id).await?;
+
+    let mut response = Response::new(data.into());
+    let headers = response.headers_mut();
+
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/zip"));
+    if let Ok(value) = HeaderValue::from_str(&format!("attachment; filename=\"{}\"", filename)) {
+        headers.insert(header::CONTENT_DISPOSITION, value);
+    }
+    headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("private, no-store"));
+
+    Ok(response)
+}
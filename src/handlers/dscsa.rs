@@ -0,0 +1,73 @@
+/// DSCSA Transaction Data REST API Handlers
+///
+/// The seller of a sale between US trading partners generates its Transaction
+/// Information, History and Statement; both parties read them from the transaction's
+/// document vault. Approval and verification go through /api/regulatory/documents/:id.
+
+use axum::{
+    extract::{Path, State},
+    Extension, Json,
+};
+use uuid::Uuid;
+use crate::{
+    config::AppConfig,
+    middleware::{error_handling::Result, Claims},
+    models::dscsa::*,
+    services::DscsaService,
+};
+
+/// GET /api/regulatory/transactions/:id/dscsa
+/// DSCSA documents attached to the transaction (seller or buyer)
+#[utoipa::path(
+    get,
+    path = "/api/regulatory/transactions/{id}/dscsa",
+    tag = "dscsa",
+    summary = "DSCSA documents attached to the transaction (seller or buyer)",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<DscsaDocument>),
+    )
+)]
+pub async fn get_transaction_dscsa_documents(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(transaction_id): Path<Uuid>,
+) -> Result<Json<Vec<DscsaDocument>>> {
+    let service = DscsaService::new(config.database_pool.clone(), &config.encryption_key)?;
+    Ok(Json(service.list(transaction_id, claims.user_id).await?))
+}
+
+/// POST /api/regulatory/transactions/:id/dscsa
+/// Generate the TI, TH and TS of a sale as signed draft regulatory documents (seller only)
+#[utoipa::path(
+    post,
+    path = "/api/regulatory/transactions/{id}/dscsa",
+    tag = "dscsa",
+    summary = "Generate the TI, TH and TS of a sale as signed draft regulatory documents (seller only)",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = DscsaDocumentSet),
+    )
+)]
+pub async fn generate_transaction_dscsa_documents(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(transaction_id): Path<Uuid>,
+) -> Result<Json<DscsaDocumentSet>> {
+    let service = DscsaService::new(config.database_pool.clone(), &config.encryption_key)?;
+    let set = service.generate(transaction_id, claims.user_id).await?;
+
+    if set.generated {
+        tracing::info!(
+            "Audit: User {} generated DSCSA transaction data for transaction {}",
+            claims.user_id,
+            transaction_id
+        );
+    }
+
+    Ok(Json(set))
+}
@@ -0,0 +1,352 @@
+// EDI API Handlers
+// Trading partner profile, exchanged documents, manual 850 (re)send and inbound X12
+// receipt over AS2 (public, token-authenticated) or manual upload
+
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Json},
+    Extension,
+};
+use base64::Engine;
+use sqlx::PgPool;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::middleware::auth::Claims;
+use crate::middleware::error_handling::{AppError, Result};
+use crate::models::edi::{EdiDocumentQuery, UpsertEdiPartnerRequest};
+use crate::services::comprehensive_audit_service::{
+    ActionResult, AuditLogEntry, ComprehensiveAuditService, EventCategory, Severity,
+};
+use crate::services::edi::transport::{build_mdn, AS2_CONTENT_TYPE};
+use crate::services::edi::EdiService;
+
+/// Interchanges larger than this are refused
+const MAX_INTERCHANGE_BYTES: usize = 10 * 1024 * 1024;
+
+fn edi_service(pool: PgPool) -> Result<EdiService> {
+    EdiService::new(pool)
+}
+
+// ============================================================================
+// Trading Partner Profile
+// ============================================================================
+
+/// GET /api/edi/partner
+#[utoipa::path(
+    get,
+    path = "/api/edi/partner",
+    tag = "edi",
+    responses(
+        (status = 200, description = "The trading partner", body = crate::models::edi::EdiPartnerResponse),
+    )
+)]
+pub async fn get_partner(
+    State(pool): State<PgPool>,
+    Extension(claims): Extension<Claims>,
+) -> Result<impl IntoResponse> {
+    let service = edi_service(pool)?;
+    let partner = service
+        .get_partner_for_user(claims.user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("No EDI trading partner profile".to_string()))?;
+
+    Ok(Json(service.partner_response(&partner, None)))
+}
+
+/// Create or replace the caller's trading partner profile
+/// PUT /api/edi/partner
+#[utoipa::path(
+    put,
+    path = "/api/edi/partner",
+    tag = "edi",
+    request_body = UpsertEdiPartnerRequest,
+    responses(
+        (status = 200, description = "The trading partner", body = crate::models::edi::EdiPartnerResponse),
+    )
+)]
+pub async fn upsert_partner(
+    State(pool): State<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<UpsertEdiPartnerRequest>,
+) -> Result<impl IntoResponse> {
+    request.validate()?;
+
+    let service = edi_service(pool.clone())?;
+    let transport = request.transport;
+    let response = service.upsert_partner(claims.user_id, request).await?;
+
+    let audit_service = ComprehensiveAuditService::new(pool);
+    audit_service
+        .log(AuditLogEntry {
+            event_type: "edi_partner_saved".to_string(),
+            event_category: EventCategory::DataModification,
+            severity: Severity::Info,
+            actor_user_id: Some(claims.user_id),
+            actor_type: "user".to_string(),
+            resource_type: Some("edi_trading_partner".to_string()),
+            resource_id: Some(response.id.to_string()),
+            action: "upsert".to_string(),
+            action_result: ActionResult::Success,
+            event_data: serde_json::json!({
+                "isa_id": response.isa_id,
+                "transport": transport.as_str(),
+                "inbound_token_issued": response.as2_inbound_token.is_some(),
+            }),
+            ..Default::default()
+        })
+        .await
+        .ok();
+
+    Ok(Json(response))
+}
+
+/// DELETE /api/edi/partner
+#[utoipa::path(
+    delete,
+    path = "/api/edi/partner",
+    tag = "edi",
+    responses(
+        (status = 204, description = "Trading partner deleted"),
+    )
+)]
+pub async fn delete_partner(
+    State(pool): State<PgPool>,
+    Extension(claims): Extension<Claims>,
+) -> Result<impl IntoResponse> {
+    let service = edi_service(pool.clone())?;
+    if !service.delete_partner(claims.user_id).await? {
+        return Err(AppError::NotFound("No EDI trading partner profile".to_string()));
+    }
+
+    let audit_service = ComprehensiveAuditService::new(pool);
+    audit_service
+        .log(AuditLogEntry {
+            event_type: "edi_partner_deleted".to_string(),
+            event_category: EventCategory::DataModification,
+            severity: Severity::Warning,
+            actor_user_id: Some(claims.user_id),
+            actor_type: "user".to_string(),
+            resource_type: Some("edi_trading_partner".to_string()),
+            action: "delete".to_string(),
+            action_result: ActionResult::Success,
+            ..Default::default()
+        })
+        .await
+        .ok();
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ============================================================================
+// Documents
+// ============================================================================
+
+/// GET /api/edi/documents?transaction_id=
+#[utoipa::path(
+    get,
+    path = "/api/edi/documents",
+    tag = "edi",
+    operation_id = "edi_list_documents",
+    params(
+        EdiDocumentQuery,
+    ),
+    responses(
+        (status = 200, description = "EDI documents", body = Vec<crate::models::edi::EdiDocument>),
+    )
+)]
+pub async fn list_documents(
+    State(pool): State<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<EdiDocumentQuery>,
+) -> Result<impl IntoResponse> {
+    let documents = edi_service(pool)?.list_documents(claims.user_id, &query).await?;
+    Ok(Json(documents))
+}
+
+/// The raw X12 interchange of a document
+/// GET /api/edi/documents/:id/raw
+#[utoipa::path(
+    get,
+    path = "/api/edi/documents/{id}/raw",
+    tag = "edi",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "The X12 interchange", body = String, content_type = "application/edi-x12"),
+    )
+)]
+pub async fn get_document_raw(
+    State(pool): State<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(document_id): Path<Uuid>,
+) -> Result<impl IntoResponse> {
+    let document = edi_service(pool)?.get_document(claims.user_id, document_id).await?;
+    Ok(([(header::CONTENT_TYPE, AS2_CONTENT_TYPE)], document.raw_x12))
+}
+
+/// Send (or retry) the 850 purchase order of a transaction to the seller's partner
+/// POST /api/edi/transactions/:id/purchase-order
+#[utoipa::path(
+    post,
+    path = "/api/edi/transactions/{id}/purchase-order",
+    tag = "edi",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "The purchase order sent", body = crate::models::edi::EdiDocument),
+    )
+)]
+pub async fn send_purchase_order(
+    State(pool): State<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(transaction_id): Path<Uuid>,
+) -> Result<impl IntoResponse> {
+    let parties: Option<(Uuid, Uuid)> = sqlx::query_as("SELECT buyer_id, seller_id FROM transactions WHERE id = $1")
+        .bind(transaction_id)
+        .fetch_optional(&pool)
+        .await?;
+
+    let (buyer_id, seller_id) = parties.ok_or_else(|| AppError::NotFound("Transaction not found".to_string()))?;
+    if claims.user_id != buyer_id && claims.user_id != seller_id {
+        return Err(AppError::Forbidden("Access denied".to_string()));
+    }
+
+    let document = edi_service(pool)?
+        .send_purchase_order(transaction_id)
+        .await?
+        .ok_or_else(|| AppError::BadRequest("The seller has no enabled EDI trading partner profile".to_string()))?;
+
+    Ok(Json(document))
+}
+
+/// Apply an interchange received outside AS2/SFTP (e.g. downloaded from a portal)
+/// as coming from the caller's own trading partner
+/// POST /api/edi/inbound
+#[utoipa::path(
+    post,
+    path = "/api/edi/inbound",
+    tag = "edi",
+    request_body(content = String, content_type = "application/edi-x12", description = "X12 interchange"),
+    responses(
+        (status = 200, description = "Documents received", body = Vec<crate::models::edi::EdiDocument>),
+    )
+)]
+pub async fn upload_interchange(
+    State(pool): State<PgPool>,
+    Extension(claims): Extension<Claims>,
+    body: Bytes,
+) -> Result<impl IntoResponse> {
+    if body.len() > MAX_INTERCHANGE_BYTES {
+        return Err(AppError::BadRequest("Interchange exceeds 10MB".to_string()));
+    }
+
+    let service = edi_service(pool)?;
+    let partner = service
+        .get_partner_for_user(claims.user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("No EDI trading partner profile".to_string()))?;
+
+    let documents = service
+        .receive_interchange(&partner, &String::from_utf8_lossy(&body), "upload")
+        .await?;
+
+    Ok(Json(documents))
+}
+
+// ============================================================================
+// AS2 Receipt (public endpoint - authenticated by the partner's inbound token)
+// ============================================================================
+
+/// Receive an AS2 message from a trading partner and answer with a synchronous MDN.
+/// The partner authenticates with HTTP basic auth, using its inbound token as password.
+/// POST /api/edi/as2/:partner_id
+#[utoipa::path(
+    post,
+    path = "/api/edi/as2/{partner_id}",
+    tag = "edi",
+    params(
+        ("partner_id" = Uuid, Path),
+    ),
+    request_body(content = String, content_type = "application/edi-x12", description = "AS2 message carrying an X12 interchange"),
+    responses(
+        (status = 200, description = "The MDN receipt", body = String, content_type = "multipart/report"),
+    )
+)]
+pub async fn as2_inbound(
+    State(pool): State<PgPool>,
+    Path(partner_id): Path<Uuid>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<impl IntoResponse> {
+    let service = edi_service(pool)?;
+    let partner = service
+        .get_partner(partner_id)
+        .await?
+        .filter(|p| p.enabled && p.transport == "as2")
+        .ok_or(AppError::Unauthorized)?;
+
+    let token = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Basic "))
+        .and_then(|encoded| base64::engine::general_purpose::STANDARD.decode(encoded.trim()).ok())
+        .and_then(|decoded| String::from_utf8(decoded).ok())
+        .and_then(|credentials| credentials.split_once(':').map(|(_, password)| password.to_string()))
+        .ok_or(AppError::Unauthorized)?;
+
+    if !service.verify_inbound_token(&partner, &token)? {
+        tracing::warn!("Rejected AS2 message for EDI partner {}: invalid token", partner_id);
+        return Err(AppError::Unauthorized);
+    }
+
+    let header_value = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.trim().trim_matches('"').to_string())
+            .unwrap_or_default()
+    };
+    let message_id = header_value("message-id");
+    let as2_from = header_value("as2-from");
+    let as2_to = header_value("as2-to");
+    let content_type = header_value("content-type").to_ascii_lowercase();
+
+    let error = if partner.as2_id.as_deref().map(|id| !id.eq_ignore_ascii_case(&as2_from)).unwrap_or(true) {
+        Some(format!("AS2-From '{}' is not the partner's AS2 id", as2_from))
+    } else if !as2_to.eq_ignore_ascii_case(&service.identity().as2_id) {
+        Some(format!("AS2-To '{}' is not Atlas ({})", as2_to, service.identity().as2_id))
+    } else if content_type.contains("pkcs7") || content_type.starts_with("multipart/signed") {
+        Some("signed or encrypted AS2 messages are not supported".to_string())
+    } else if body.len() > MAX_INTERCHANGE_BYTES {
+        Some("interchange exceeds 10MB".to_string())
+    } else {
+        match service.receive_interchange(&partner, &String::from_utf8_lossy(&body), "as2").await {
+            Ok(documents) => {
+                tracing::info!("AS2 message {} from partner {}: {} documents", message_id, partner_id, documents.len());
+                None
+            }
+            Err(e) => Some(e.to_string()),
+        }
+    };
+
+    if let Some(reason) = &error {
+        tracing::warn!("AS2 message {} from partner {} not processed: {}", message_id, partner_id, reason);
+    }
+
+    let (mdn_content_type, mdn) = build_mdn(&message_id, &service.identity().as2_id, error.as_deref());
+    Ok((
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, mdn_content_type),
+            (header::HeaderName::from_static("as2-version"), "1.2".to_string()),
+            (header::HeaderName::from_static("as2-from"), service.identity().as2_id.clone()),
+            (header::HeaderName::from_static("as2-to"), as2_from),
+        ],
+        mdn,
+    ))
+}
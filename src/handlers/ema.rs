@@ -1,20 +1,26 @@
 use axum::{
     extract::{Path, Query, State},
+    response::{IntoResponse, Response},
     Json,
     Extension,
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
 };
+use uuid::Uuid;
 use crate::{
     models::ema::{
-        EmaSearchRequest, EmaCatalogResponse, EmaCatalogStats, EmaSyncLog
+        EmaSearchRequest, EmaCatalogResponse, EmaCatalogStats, EmaSyncLog,
+        EmaDocument, EmaDocumentQuery, EmaDocumentSearchRequest, EmaDocumentSearchResult,
+        EmaSyncProgressResponse
     },
-    services::ema_service::EmaService,
+    models::catalog_export::EmaExportParams,
+    services::{ema_service::EmaService, ema_document_service::EmaDocumentService, CatalogExportService},
+    handlers::openfda::{export_response, records_range_offset},
     repositories::ema_repo::EmaRepository,
     middleware::{
-        error_handling::Result,
+        error_handling::{AppError, Result},
         auth::Claims
     },
-    config::AppConfig,
+    config::{AppConfig, ReadConsistency},
 };
 
 /// Search EMA catalog with full-text search and filters
@@ -26,24 +32,39 @@ use crate::{
 /// - `therapeutic_area`: Filter by therapeutic area
 /// - `atc_code`: Filter by ATC code
 /// - `mah_name`: Filter by Marketing Authorization Holder name
+/// - `facets`: Comma-separated fields to count values of (search engine only)
 /// - `limit`: Maximum number of results (default: 20, max: 100)
 /// - `offset`: Offset for pagination (default: 0)
 ///
 /// # Response:
-/// Returns array of EMA catalog entries matching search criteria
+/// Returns array of EMA catalog entries matching search criteria; with `facets`, an
+/// object with the `results`, the `facets` counts, the `total` and the `engine` used
+#[utoipa::path(
+    get,
+    path = "/api/ema/search",
+    tag = "ema",
+    operation_id = "ema_search_catalog",
+    params(
+        EmaSearchRequest,
+    ),
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
 pub async fn search_catalog(
     State(config): State<AppConfig>,
     Query(request): Query<EmaSearchRequest>,
-) -> Result<Json<Vec<EmaCatalogResponse>>> {
+) -> Result<Json<serde_json::Value>> {
     // Validate language if provided
     if let Some(ref lang) = request.language {
         let ema_service = EmaService::new(EmaRepository::new(config.database_pool.clone()));
         ema_service.validate_language(lang)?;
     }
 
-    let ema_service = EmaService::new(EmaRepository::new(config.database_pool.clone()));
-    let results = ema_service.search(request).await?;
-    Ok(Json(results))
+    let ema_service = EmaService::new(EmaRepository::new(config.read_pool(ReadConsistency::Relaxed)));
+    let with_facets = request.facets.is_some();
+    let results = ema_service.search(request, config.search_engine.as_ref()).await?;
+    Ok(Json(results.into_body(with_facets)?))
 }
 
 /// Get medicine by EU number
@@ -53,6 +74,17 @@ pub async fn search_catalog(
 ///
 /// # Response:
 /// Returns the EMA catalog entry if found, otherwise null
+#[utoipa::path(
+    get,
+    path = "/api/ema/eu/{eu_number}",
+    tag = "ema",
+    params(
+        ("eu_number" = String, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = Option<EmaCatalogResponse>),
+    )
+)]
 pub async fn get_by_eu_number(
     State(config): State<AppConfig>,
     Path(eu_number): Path<String>,
@@ -65,6 +97,92 @@ pub async fn get_by_eu_number(
     Ok(Json(result))
 }
 
+/// List downloaded product information documents (ePI, SmPC, PIL) for a medicine
+///
+/// # Path Parameters:
+/// - `eu_number`: The EU number of the medicine
+///
+/// # Query Parameters:
+/// - `language`: Only documents in this language
+/// - `include_text`: Include the extracted document text (default: false)
+///
+/// # Response:
+/// Returns document metadata (content hash, size, fetch/change times); documents are
+/// only available when `EMA_DOCUMENT_FETCH_ENABLED` is set for syncs
+#[utoipa::path(
+    get,
+    path = "/api/ema/eu/{eu_number}/documents",
+    tag = "ema",
+    params(
+        ("eu_number" = String, Path),
+        EmaDocumentQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<EmaDocument>),
+    )
+)]
+pub async fn get_documents(
+    State(config): State<AppConfig>,
+    Path(eu_number): Path<String>,
+    Query(query): Query<EmaDocumentQuery>,
+) -> Result<Json<Vec<EmaDocument>>> {
+    let ema_service = EmaService::new(EmaRepository::new(config.database_pool.clone()));
+    ema_service.validate_eu_number(&eu_number)?;
+    if let Some(ref lang) = query.language {
+        ema_service.validate_language(lang)?;
+    }
+
+    let document_service = EmaDocumentService::new(
+        config.database_pool.clone(),
+        &config.file_storage_path,
+        &config.encryption_key,
+    )?;
+    let documents = document_service
+        .list_documents(&eu_number, query.language.as_deref(), query.include_text.unwrap_or(false))
+        .await?;
+    Ok(Json(documents))
+}
+
+/// Full-text search across downloaded product information documents
+///
+/// # Query Parameters:
+/// - `q`: Search terms
+/// - `language`: Filter by language code
+/// - `document_type`: `epi`, `smpc` or `pil`
+/// - `limit`: Maximum number of results (default: 20, max: 100)
+/// - `offset`: Offset for pagination (default: 0)
+///
+/// # Response:
+/// Returns matching documents ranked by relevance with a highlighted excerpt
+#[utoipa::path(
+    get,
+    path = "/api/ema/documents/search",
+    tag = "ema",
+    params(
+        EmaDocumentSearchRequest,
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<EmaDocumentSearchResult>),
+    )
+)]
+pub async fn search_documents(
+    State(config): State<AppConfig>,
+    Query(request): Query<EmaDocumentSearchRequest>,
+) -> Result<Json<Vec<EmaDocumentSearchResult>>> {
+    if let Some(ref lang) = request.language {
+        let ema_service = EmaService::new(EmaRepository::new(config.database_pool.clone()));
+        ema_service.validate_language(lang)?;
+    }
+
+    let document_service = EmaDocumentService::new(
+        config.database_pool.clone(),
+        &config.file_storage_path,
+        &config.encryption_key,
+    )?;
+    let results = document_service.search(&request).await?;
+    Ok(Json(results))
+}
+
 /// Get catalog statistics and metadata
 ///
 /// # Response:
@@ -73,14 +191,58 @@ pub async fn get_by_eu_number(
 /// - Counts by language, status, therapeutic area
 /// - Orphan medicines count
 /// - Last sync information
+#[utoipa::path(
+    get,
+    path = "/api/ema/stats",
+    tag = "ema",
+    operation_id = "ema_get_stats",
+    responses(
+        (status = 200, description = "Success", body = EmaCatalogStats),
+    )
+)]
 pub async fn get_stats(
     State(config): State<AppConfig>,
 ) -> Result<Json<EmaCatalogStats>> {
-    let ema_service = EmaService::new(EmaRepository::new(config.database_pool.clone()));
+    let ema_service = EmaService::new(EmaRepository::new(config.read_pool(ReadConsistency::Relaxed)));
     let stats = ema_service.get_stats().await?;
     Ok(Json(stats))
 }
 
+/// Stream the catalog as CSV or NDJSON
+///
+/// # Query Parameters:
+/// - `format`: `csv` (default) or `ndjson`
+/// - `language`, `authorization_status`, `therapeutic_area`, `atc_code` (prefix), `mah_name`,
+///   `orphan_designation`, `updated_since`: filters
+/// - `after`: resume after this EU number
+///
+/// # Headers:
+/// - `Range: records=N-`: skip the first N records (answered with 206 and `Content-Range`)
+#[utoipa::path(
+    get,
+    path = "/api/ema/export",
+    tag = "ema",
+    operation_id = "ema_export_catalog",
+    params(
+        EmaExportParams,
+    ),
+    responses(
+        (status = 200, description = "The catalog as CSV or NDJSON", body = String, content_type = "text/csv"),
+        (status = 206, description = "A range of the catalog", body = String, content_type = "text/csv"),
+    )
+)]
+pub async fn export_catalog(
+    State(config): State<AppConfig>,
+    headers: HeaderMap,
+    Query(params): Query<EmaExportParams>,
+) -> Result<Response> {
+    let offset = records_range_offset(&headers)?;
+    let service = CatalogExportService::new(config.read_pool(ReadConsistency::Relaxed));
+    let export = service.export_ema(params, offset.unwrap_or(0)).await?;
+
+    Ok(export_response(export, "ema-catalog", offset.is_some()))
+}
+
 /// Get synchronization logs with pagination
 ///
 /// # Query Parameters:
@@ -89,6 +251,19 @@ pub async fn get_stats(
 ///
 /// # Response:
 /// Returns array of sync log entries
+#[utoipa::path(
+    get,
+    path = "/api/ema/sync/logs",
+    tag = "ema",
+    operation_id = "ema_get_sync_logs",
+    params(
+        ("limit" = Option<i64>, Query),
+        ("offset" = Option<i64>, Query),
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<EmaSyncLog>),
+    )
+)]
 pub async fn get_sync_logs(
     State(config): State<AppConfig>,
     Query(params): Query<serde_json::Value>,
@@ -113,17 +288,35 @@ pub async fn get_sync_logs(
 /// - `language`: Language to sync (default: en)
 /// - `limit`: Maximum number of records to sync (default: 1000)
 /// - `sync_type`: Type of sync - "full", "incremental", "by_language" (default: full)
+/// - `dry_run`: When `true`, fetch up to `limit` entries (default: 500) and return a
+///   sync preview of what would be inserted or updated, without writing anything
 ///
 /// # Response:
-/// Returns sync log information about the triggered sync operation
+/// Returns sync log information about the triggered sync operation,
+/// or a sync preview for dry runs
 ///
 /// # Security:
 /// Requires admin authentication
+#[utoipa::path(
+    post,
+    path = "/api/ema/sync",
+    tag = "ema",
+    operation_id = "ema_trigger_sync",
+    params(
+        ("language" = Option<String>, Query),
+        ("limit" = Option<i64>, Query),
+        ("sync_type" = Option<String>, Query),
+        ("dry_run" = Option<bool>, Query),
+    ),
+    responses(
+        (status = 200, description = "The sync log; a SyncPreview with dry_run=true", body = EmaSyncLog),
+    )
+)]
 pub async fn trigger_sync(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
     Query(params): Query<serde_json::Value>,
-) -> Result<Json<EmaSyncLog>> {
+) -> Result<Response> {
     // Verify admin role
     if !claims.is_admin() {
         return Err(crate::middleware::error_handling::AppError::Forbidden(
@@ -131,6 +324,41 @@ pub async fn trigger_sync(
         ));
     }
 
+    let ema_service = EmaService::new(EmaRepository::new(config.database_pool.clone()));
+    let (language, limit, sync_type) = parse_sync_params(&params, &ema_service)?;
+
+    let dry_run = match params.get("dry_run") {
+        Some(serde_json::Value::Bool(b)) => *b,
+        Some(serde_json::Value::String(s)) => s.eq_ignore_ascii_case("true"),
+        _ => false,
+    };
+    if dry_run {
+        tracing::info!("Admin '{}' running EMA sync dry run: language={:?}, limit={:?}", claims.sub, language, limit);
+        let preview = ema_service.preview_sync(language, limit).await?;
+        return Ok(Json(preview).into_response());
+    }
+
+    // Trigger sync operation
+    tracing::info!(
+        "Admin '{}' triggering EMA sync: language={:?}, limit={:?}, sync_type={:?}",
+        claims.sub, language, limit, sync_type
+    );
+
+    let sync_log = ema_service.sync_from_api(language, limit, sync_type).await?;
+
+    tracing::info!(
+        "EMA sync triggered successfully. Sync log ID: {}, Status: {}",
+        sync_log.id, sync_log.status
+    );
+
+    Ok(Json(sync_log).into_response())
+}
+
+/// Extract and validate `language`, `limit` and `sync_type` sync parameters
+fn parse_sync_params(
+    params: &serde_json::Value,
+    ema_service: &EmaService,
+) -> Result<(Option<String>, Option<usize>, Option<String>)> {
     // Extract parameters with validation
     let language = params.get("language")
         .and_then(|v| v.as_str())
@@ -145,8 +373,6 @@ pub async fn trigger_sync(
         .map(|s| s.to_string());
 
     // Validate parameters
-    let ema_service = EmaService::new(EmaRepository::new(config.database_pool.clone()));
-
     if let Some(ref lang) = language {
         ema_service.validate_language(lang)?;
     }
@@ -159,20 +385,132 @@ pub async fn trigger_sync(
         }
     }
 
-    // Trigger sync operation
+    Ok((language, limit, sync_type))
+}
+
+/// Start a sync in the background (admin only)
+///
+/// # Query Parameters:
+/// Same as `POST /api/ema/sync`
+///
+/// # Response:
+/// Returns the sync ID immediately; poll `/api/ema/sync/{id}` for progress
+///
+/// # Security:
+/// Requires admin authentication
+#[utoipa::path(
+    post,
+    path = "/api/ema/sync/background",
+    tag = "ema",
+    params(
+        ("language" = Option<String>, Query),
+        ("limit" = Option<i64>, Query),
+        ("sync_type" = Option<String>, Query),
+    ),
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
+pub async fn trigger_background_sync(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Query(params): Query<serde_json::Value>,
+) -> Result<Json<serde_json::Value>> {
+    crate::require_admin!(claims);
+
+    let ema_service = EmaService::new(EmaRepository::new(config.database_pool.clone()));
+    let (language, limit, sync_type) = parse_sync_params(&params, &ema_service)?;
+
     tracing::info!(
-        "Admin '{}' triggering EMA sync: language={:?}, limit={:?}, sync_type={:?}",
+        "Admin '{}' starting background EMA sync: language={:?}, limit={:?}, sync_type={:?}",
         claims.sub, language, limit, sync_type
     );
 
-    let sync_log = ema_service.sync_from_api(language, limit, sync_type).await?;
+    let sync_id = ema_service.start_background_sync(language, limit, sync_type).await?;
 
-    tracing::info!(
-        "EMA sync triggered successfully. Sync log ID: {}, Status: {}",
-        sync_log.id, sync_log.status
-    );
+    Ok(Json(serde_json::json!({
+        "sync_id": sync_id,
+        "message": "EMA sync started in background. Use /api/ema/sync/{id} to check progress."
+    })))
+}
+
+/// Get sync progress by ID
+#[utoipa::path(
+    get,
+    path = "/api/ema/sync/{sync_id}",
+    tag = "ema",
+    operation_id = "ema_get_sync_progress",
+    params(
+        ("sync_id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = EmaSyncProgressResponse),
+    )
+)]
+pub async fn get_sync_progress(
+    State(config): State<AppConfig>,
+    Path(sync_id): Path<Uuid>,
+) -> Result<Json<EmaSyncProgressResponse>> {
+    let ema_service = EmaService::new(EmaRepository::new(config.database_pool.clone()));
 
-    Ok(Json(sync_log))
+    match ema_service.get_sync_progress(sync_id).await? {
+        Some(progress) => Ok(Json(progress)),
+        None => Err(AppError::NotFound(format!("Sync log {} not found", sync_id))),
+    }
+}
+
+/// Get active sync (if any)
+#[utoipa::path(
+    get,
+    path = "/api/ema/sync/active",
+    tag = "ema",
+    operation_id = "ema_get_active_sync",
+    responses(
+        (status = 200, description = "Success", body = Option<EmaSyncProgressResponse>),
+    )
+)]
+pub async fn get_active_sync(
+    State(config): State<AppConfig>,
+) -> Result<Json<Option<EmaSyncProgressResponse>>> {
+    let ema_service = EmaService::new(EmaRepository::new(config.database_pool.clone()));
+    let active = ema_service.get_active_sync().await?;
+    Ok(Json(active))
+}
+
+/// Cancel a running sync (admin only)
+///
+/// # Response:
+/// Returns JSON object with `cancelled` (false if the sync is not in progress)
+#[utoipa::path(
+    post,
+    path = "/api/ema/sync/{sync_id}/cancel",
+    tag = "ema",
+    operation_id = "ema_cancel_sync",
+    params(
+        ("sync_id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
+pub async fn cancel_sync(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(sync_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>> {
+    crate::require_admin!(claims);
+
+    let ema_service = EmaService::new(EmaRepository::new(config.database_pool.clone()));
+    let cancelled = ema_service.cancel_sync(sync_id, claims.user_id).await?;
+
+    Ok(Json(serde_json::json!({
+        "cancelled": cancelled,
+        "message": if cancelled {
+            "Sync cancellation requested"
+        } else {
+            "Sync not found or not in progress"
+        }
+    })))
 }
 
 /// Check if catalog needs refresh
@@ -182,6 +520,18 @@ pub async fn trigger_sync(
 ///
 /// # Response:
 /// Returns JSON object with `needs_refresh` boolean field
+#[utoipa::path(
+    get,
+    path = "/api/ema/refresh-status",
+    tag = "ema",
+    operation_id = "ema_check_refresh_status",
+    params(
+        ("days_threshold" = Option<i64>, Query),
+    ),
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
 pub async fn check_refresh_status(
     State(config): State<AppConfig>,
     Query(params): Query<serde_json::Value>,
@@ -204,6 +554,14 @@ pub async fn check_refresh_status(
 ///
 /// # Response:
 /// Returns configuration information including API URLs, supported languages, etc.
+#[utoipa::path(
+    get,
+    path = "/api/ema/config",
+    tag = "ema",
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
 pub async fn get_config_info(
     State(config): State<AppConfig>,
 ) -> Result<Json<serde_json::Value>> {
@@ -231,6 +589,15 @@ pub async fn get_config_info(
 ///
 /// # Security:
 /// Requires admin authentication
+#[utoipa::path(
+    post,
+    path = "/api/ema/cleanup",
+    tag = "ema",
+    operation_id = "ema_cleanup_sync_logs",
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
 pub async fn cleanup_sync_logs(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -260,6 +627,15 @@ pub async fn cleanup_sync_logs(
 ///
 /// # Response:
 /// Returns health status including database connectivity and last sync info
+#[utoipa::path(
+    get,
+    path = "/api/ema/health",
+    tag = "ema",
+    operation_id = "ema_health_check",
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
 pub async fn health_check(
     State(config): State<AppConfig>,
 ) -> Result<Json<serde_json::Value>> {
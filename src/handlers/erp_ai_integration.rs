@@ -9,6 +9,7 @@ use axum::{
     Extension,
 };
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use sqlx::PgPool;
 use uuid::Uuid;
 
@@ -31,12 +32,12 @@ pub struct AnalyzeSyncRequest {
     pub sync_log_id: Uuid,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct ResolveConflictsRequest {
     pub conflicts: Vec<ConflictInput>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
 pub struct ConflictInput {
     pub atlas_inventory_id: Uuid,
     pub erp_item_id: String,
@@ -47,7 +48,7 @@ pub struct ConflictInput {
     pub erp_updated_at: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct MappingDiscoveryStatusResponse {
     pub discovery_in_progress: bool,
     pub total_suggestions: usize,
@@ -59,7 +60,7 @@ pub struct MappingDiscoveryStatusResponse {
     pub rejected_count: usize,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct MappingSuggestionResponse {
     pub id: Uuid,
     pub atlas_inventory_id: Option<Uuid>,
@@ -72,7 +73,7 @@ pub struct MappingSuggestionResponse {
     pub created_at: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct ReviewMappingRequest {
     pub status: String,  // "accepted", "rejected", "skipped"
 }
@@ -83,6 +84,18 @@ pub struct ReviewMappingRequest {
 
 /// POST /api/erp/connections/{connection_id}/auto-discover-mappings
 /// Trigger AI auto-discovery of inventory mappings
+#[utoipa::path(
+    post,
+    path = "/api/erp/connections/{id}/auto-discover-mappings",
+    tag = "erp_ai_integration",
+    summary = "Trigger AI auto-discovery of inventory mappings",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Suggested mappings", body = MappingDiscoveryResponse),
+    )
+)]
 pub async fn auto_discover_mappings(
     State(pool): State<PgPool>,
     Extension(claims): Extension<Claims>,
@@ -154,6 +167,18 @@ pub async fn auto_discover_mappings(
 
 /// GET /api/erp/connections/{connection_id}/mapping-suggestions
 /// Get AI-suggested mappings for review
+#[utoipa::path(
+    get,
+    path = "/api/erp/connections/{id}/mapping-suggestions",
+    tag = "erp_ai_integration",
+    summary = "Get AI-suggested mappings for review",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Mapping suggestions", body = Vec<MappingSuggestionResponse>),
+    )
+)]
 pub async fn get_mapping_suggestions(
     State(pool): State<PgPool>,
     Extension(claims): Extension<Claims>,
@@ -190,6 +215,20 @@ pub async fn get_mapping_suggestions(
 
 /// POST /api/erp/connections/{connection_id}/mapping-suggestions/{suggestion_id}/review
 /// Review and accept/reject AI mapping suggestion
+#[utoipa::path(
+    post,
+    path = "/api/erp/connections/{id}/mapping-suggestions/{suggestion_id}/review",
+    tag = "erp_ai_integration",
+    summary = "Review and accept/reject AI mapping suggestion",
+    params(
+        ("id" = Uuid, Path),
+        ("suggestion_id" = Uuid, Path),
+    ),
+    request_body = ReviewMappingRequest,
+    responses(
+        (status = 200, description = "Suggestion reviewed", body = serde_json::Value),
+    )
+)]
 pub async fn review_mapping_suggestion(
     State(pool): State<PgPool>,
     Extension(claims): Extension<Claims>,
@@ -292,6 +331,18 @@ pub async fn review_mapping_suggestion(
 
 /// GET /api/erp/sync-logs/{sync_log_id}/ai-analysis
 /// Get AI analysis of sync operation
+#[utoipa::path(
+    get,
+    path = "/api/erp/sync-logs/{id}/ai-analysis",
+    tag = "erp_ai_integration",
+    summary = "Get AI analysis of sync operation",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Analysis of the sync", body = SyncInsight),
+    )
+)]
 pub async fn get_sync_analysis(
     State(pool): State<PgPool>,
     Extension(claims): Extension<Claims>,
@@ -349,6 +400,19 @@ pub async fn get_sync_analysis(
 
 /// POST /api/erp/connections/{connection_id}/resolve-conflicts
 /// Get AI suggestions for conflict resolution
+#[utoipa::path(
+    post,
+    path = "/api/erp/connections/{id}/resolve-conflicts",
+    tag = "erp_ai_integration",
+    summary = "Get AI suggestions for conflict resolution",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    request_body = ResolveConflictsRequest,
+    responses(
+        (status = 200, description = "Suggested resolutions", body = ConflictResolutionResponse),
+    )
+)]
 pub async fn suggest_conflict_resolution(
     State(pool): State<PgPool>,
     Extension(claims): Extension<Claims>,
@@ -436,6 +500,18 @@ pub async fn suggest_conflict_resolution(
 
 /// GET /api/erp/connections/{connection_id}/mapping-status
 /// Get mapping discovery status and statistics
+#[utoipa::path(
+    get,
+    path = "/api/erp/connections/{id}/mapping-status",
+    tag = "erp_ai_integration",
+    summary = "Get mapping discovery status and statistics",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Mapping discovery status", body = MappingDiscoveryStatusResponse),
+    )
+)]
 pub async fn get_mapping_status(
     State(pool): State<PgPool>,
     Extension(claims): Extension<Claims>,
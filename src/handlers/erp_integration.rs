@@ -1,5 +1,5 @@
 // ERP Integration API Handlers
-// Production-ready REST endpoints for Oracle NetSuite and SAP S/4HANA integration
+// Production-ready REST endpoints for Oracle NetSuite, SAP S/4HANA and flat-file (CSV over SFTP) integration
 // Comprehensive validation, error handling, and audit logging
 
 use axum::{
@@ -9,17 +9,29 @@ use axum::{
     Extension,
 };
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 use sqlx::PgPool;
 use uuid::Uuid;
 
 use crate::middleware::auth::Claims;
 use crate::middleware::error_handling::{AppError, Result};
+use crate::models::background_job::{ErpSyncJob, JobType, NewJob};
+use crate::services::BackgroundJobService;
 use crate::services::erp::{
-    ErpConnectionService, ErpSyncService, ErpType, SyncDirection,
+    ErpConnectionService, ErpFieldMappingService, ErpSyncService, ErpType,
 };
 use crate::services::erp::erp_connection_service::{
-    CreateConnectionRequest, ConnectionResponse, ConnectionTestResult,
+    CreateConnectionRequest, ConnectionResponse, ConnectionTestResult, ErpConnection, ErpEntity, ErpEntityInput,
+    ErpItemDetails,
 };
+use crate::services::erp::flat_file_format::{
+    parse_csv, FlatFileColumnMapping, FlatFileField, FlatFileRow, FlatFileRowError, FLAT_FILE_FIELDS,
+};
+use crate::services::erp::erp_conflict_service::{ConflictResolutionOutcome, ErpConflictService, ResolveConflictRequest};
+use crate::services::erp::erp_field_mapping_service::{FieldMappingTemplate, SaveFieldMappingTemplateRequest};
+use crate::services::erp::erp_item_catalog_service::{CatalogQuery, ErpItemCatalogService};
+use crate::services::erp::webhook_events::{parse_netsuite_event, parse_sap_event};
+use crate::services::erp::SftpClient;
 use crate::services::comprehensive_audit_service::{
     ComprehensiveAuditService, AuditLogEntry, EventCategory, Severity, ActionResult,
 };
@@ -33,7 +45,7 @@ use axum::http::HeaderMap;
 // Request/Response DTOs
 // ============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateErpConnectionRequest {
     pub connection_name: String,
     pub erp_type: String,
@@ -55,6 +67,19 @@ pub struct CreateErpConnectionRequest {
     pub sap_plant: Option<String>,
     pub sap_company_code: Option<String>,
 
+    // Flat-file (CSV over SFTP) settings
+    pub sftp_host: Option<String>,
+    pub sftp_port: Option<i32>,
+    pub sftp_username: Option<String>,
+    pub sftp_password: Option<String>,
+    pub sftp_private_key: Option<String>,
+    pub sftp_host_key_fingerprint: Option<String>,
+    pub sftp_inbound_path: Option<String>,
+    pub sftp_outbound_path: Option<String>,
+    pub sftp_archive_path: Option<String>,
+    pub sftp_file_pattern: Option<String>,
+    pub csv_column_mapping: Option<FlatFileColumnMapping>,
+
     // Sync configuration
     pub sync_enabled: Option<bool>,
     pub sync_frequency_minutes: Option<i32>,
@@ -71,22 +96,27 @@ pub struct UpdateConnectionRequest {
     pub sync_frequency_minutes: Option<i32>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct SyncQueryParams {
     pub direction: Option<String>,  // "atlas_to_erp", "erp_to_atlas", "bidirectional"
+    /// Discard the delta sync cursor so ERP -> Atlas reads every item
+    pub full: Option<bool>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ErpConnectionListResponse {
     pub connections: Vec<ConnectionResponse>,
     pub total: usize,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct SyncResponse {
     pub sync_started: bool,
     pub message: String,
     pub sync_log_id: Option<Uuid>,
+    /// Background job running the sync
+    pub job_id: Option<Uuid>,
 }
 
 #[derive(Debug, Serialize)]
@@ -107,19 +137,173 @@ pub struct SyncErrorDetail {
     pub error_type: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
 pub struct MappingResponse {
     pub id: Uuid,
     pub atlas_inventory_id: Uuid,
     pub erp_item_id: String,
     pub erp_item_name: Option<String>,
     pub erp_location_id: Option<String>,
+    pub erp_location_name: Option<String>,
+    pub erp_entity_id: Option<String>,
     pub sync_enabled: bool,
+    pub sync_direction: String,
+    pub conflict_resolution: Option<String>,
     pub last_synced_at: Option<chrono::DateTime<chrono::Utc>>,
     pub last_sync_status: Option<String>,
+    pub erp_item_fetched_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
-#[derive(Debug, Serialize)]
+/// Body of manual mapping creation and edits (PUT replaces every field)
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SaveMappingRequest {
+    pub atlas_inventory_id: Uuid,
+    pub erp_item_id: String,
+    pub erp_location_id: Option<String>,
+    /// Subsidiary or plant of the connection (default: the connection default)
+    pub erp_entity_id: Option<String>,
+    pub sync_enabled: Option<bool>,         // default true
+    pub sync_direction: Option<String>,     // "atlas_to_erp", "erp_to_atlas", "bidirectional" (default), "disabled"
+    pub conflict_resolution: Option<String>, // "inherit" (default), "atlas_wins", "erp_wins", "manual", "latest_timestamp"
+}
+
+impl SaveMappingRequest {
+    /// Trim identifiers, apply defaults and check the enumerations
+    fn normalize(mut self) -> Result<Self> {
+        self.erp_item_id = self.erp_item_id.trim().to_string();
+        self.erp_location_id = self.erp_location_id
+            .map(|l| l.trim().to_string())
+            .filter(|l| !l.is_empty());
+        self.erp_entity_id = self.erp_entity_id
+            .map(|e| e.trim().to_string())
+            .filter(|e| !e.is_empty());
+
+        if self.erp_item_id.is_empty() || self.erp_item_id.len() > 100 {
+            return Err(AppError::BadRequest("erp_item_id must be 1-100 characters".to_string()));
+        }
+        if self.erp_location_id.as_ref().map(|l| l.len() > 100).unwrap_or(false) {
+            return Err(AppError::BadRequest("erp_location_id must be at most 100 characters".to_string()));
+        }
+
+        let direction = self.sync_direction.get_or_insert_with(|| "bidirectional".to_string());
+        if !["atlas_to_erp", "erp_to_atlas", "bidirectional", "disabled"].contains(&direction.as_str()) {
+            return Err(AppError::BadRequest(format!("Invalid sync_direction: {}", direction)));
+        }
+
+        let resolution = self.conflict_resolution.get_or_insert_with(|| "inherit".to_string());
+        if !["inherit", "atlas_wins", "erp_wins", "manual", "latest_timestamp"].contains(&resolution.as_str()) {
+            return Err(AppError::BadRequest(format!("Invalid conflict_resolution: {}", resolution)));
+        }
+
+        self.sync_enabled.get_or_insert(true);
+        Ok(self)
+    }
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
+pub struct PendingMappingResponse {
+    pub id: Uuid,
+    pub erp_item_id: String,
+    pub erp_location_id: Option<String>,
+    pub erp_item_name: Option<String>,
+    pub ndc_code: Option<String>,
+    pub last_quantity: Option<i32>,
+    pub last_event_type: String,
+    pub occurrences: i32,
+    pub status: String,
+    pub erp_mapping_id: Option<Uuid>,
+    pub first_seen_at: chrono::DateTime<chrono::Utc>,
+    pub last_seen_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Filter of sync logs and mappings by subsidiary / plant
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EntityFilterQuery {
+    pub entity_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PendingMappingQuery {
+    /// "pending" (default), "mapped", "ignored" or "all"
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ResolvePendingMappingRequest {
+    /// "map" or "ignore"
+    pub action: String,
+    /// Required for "map"
+    pub atlas_inventory_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FieldMappingTemplateQuery {
+    pub erp_type: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AssignFieldMappingRequest {
+    /// None reverts the connection to the system default template
+    pub template_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ConflictQuery {
+    /// "pending" (default), "resolved", "ignored", "auto_resolved" or "all"
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ColumnMappingResponse {
+    pub mapping: FlatFileColumnMapping,
+    #[schema(value_type = Vec<FlatFileField>)]
+    pub fields: &'static [FlatFileField],
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ColumnMappingPreviewRequest {
+    /// Mapping to try (default: the saved mapping)
+    pub mapping: Option<FlatFileColumnMapping>,
+    /// CSV text to parse (default: the newest file in the inbound directory)
+    pub sample_csv: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ColumnMappingPreviewResponse {
+    pub file_name: Option<String>,
+    pub headers: Vec<String>,
+    pub unmapped_headers: Vec<String>,
+    pub total_rows: usize,
+    pub rows: Vec<FlatFileRow>,
+    pub error_count: usize,
+    pub errors: Vec<FlatFileRowError>,
+    /// Set when the mapped columns are missing from the file
+    pub file_error: Option<String>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
+pub struct FlatFileTransferResponse {
+    pub id: Uuid,
+    pub sync_log_id: Option<Uuid>,
+    pub direction: String,
+    pub file_name: String,
+    pub file_size: i64,
+    pub checksum_sha256: Option<String>,
+    pub status: String,
+    pub rows_total: i32,
+    pub rows_failed: i32,
+    pub error_message: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+const PREVIEW_ROWS: usize = 20;
+const PREVIEW_ERRORS: usize = 50;
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct SyncLogResponse {
     pub id: Uuid,
     pub sync_type: String,
@@ -131,16 +315,94 @@ pub struct SyncLogResponse {
     pub items_skipped: i32,
     pub duration_seconds: Option<i32>,
     pub error_message: Option<String>,
+    pub sandbox: bool,
+    /// Subsidiaries / plants the run covered
+    pub entity_ids: Option<Vec<String>>,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub completed_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SandboxModeRequest {
+    pub enabled: bool,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SandboxWriteQuery {
+    /// Writes captured by one sync; the most recent writes of the connection otherwise
+    pub sync_log_id: Option<Uuid>,
+}
+
+/// An ERP write captured in sandbox mode, as a diff of the current and proposed values
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
+pub struct SandboxWriteResponse {
+    pub id: Uuid,
+    pub erp_sync_log_id: Option<Uuid>,
+    pub erp_mapping_id: Option<Uuid>,
+    pub erp_item_id: String,
+    pub erp_location_id: Option<String>,
+    pub operation: String,
+    pub current_value: Option<serde_json::Value>,
+    pub proposed_value: serde_json::Value,
+    /// False when the write would leave the ERP value unchanged
+    pub changed: bool,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct OrderExportSettingsRequest {
+    pub export_sales_orders: bool,
+    pub export_purchase_orders: bool,
+    /// ERP customer marketplace sales orders are booked against
+    pub order_customer_id: Option<String>,
+    /// ERP vendor/supplier marketplace purchase orders are booked against
+    pub order_vendor_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct OrderExportSettingsResponse {
+    pub connection_id: Uuid,
+    pub sync_transactions: bool,
+    pub export_sales_orders: bool,
+    pub export_purchase_orders: bool,
+    pub order_customer_id: Option<String>,
+    pub order_vendor_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ReplaceEntitiesRequest {
+    pub entities: Vec<ErpEntityInput>,
+}
+
+impl From<&ErpConnection> for OrderExportSettingsResponse {
+    fn from(connection: &ErpConnection) -> Self {
+        Self {
+            connection_id: connection.id,
+            sync_transactions: connection.sync_transactions,
+            export_sales_orders: connection.export_sales_orders,
+            export_purchase_orders: connection.export_purchase_orders,
+            order_customer_id: connection.order_customer_id.clone(),
+            order_vendor_id: connection.order_vendor_id.clone(),
+        }
+    }
+}
+
 // ============================================================================
 // Connection Management Handlers
 // ============================================================================
 
 /// Create a new ERP connection
 /// POST /api/erp/connections
+#[utoipa::path(
+    post,
+    path = "/api/erp/connections",
+    tag = "erp_integration",
+    request_body = CreateErpConnectionRequest,
+    responses(
+        (status = 201, description = "Connection created", body = ConnectionResponse),
+    )
+)]
 pub async fn create_connection(
     State(pool): State<PgPool>,
     Extension(claims): Extension<Claims>,
@@ -157,9 +419,10 @@ pub async fn create_connection(
     let erp_type = match request.erp_type.to_lowercase().as_str() {
         "netsuite" => ErpType::NetSuite,
         "sap_s4hana" => ErpType::SapS4Hana,
+        "flat_file" => ErpType::FlatFile,
         _ => {
             return Err(AppError::BadRequest(format!(
-                "Invalid ERP type: {}. Must be 'netsuite' or 'sap_s4hana'",
+                "Invalid ERP type: {}. Must be 'netsuite', 'sap_s4hana' or 'flat_file'",
                 request.erp_type
             )));
         }
@@ -182,6 +445,17 @@ pub async fn create_connection(
         sap_environment: request.sap_environment,
         sap_plant: request.sap_plant,
         sap_company_code: request.sap_company_code,
+        sftp_host: request.sftp_host,
+        sftp_port: request.sftp_port,
+        sftp_username: request.sftp_username,
+        sftp_password: request.sftp_password,
+        sftp_private_key: request.sftp_private_key,
+        sftp_host_key_fingerprint: request.sftp_host_key_fingerprint,
+        sftp_inbound_path: request.sftp_inbound_path,
+        sftp_outbound_path: request.sftp_outbound_path,
+        sftp_archive_path: request.sftp_archive_path,
+        sftp_file_pattern: request.sftp_file_pattern,
+        csv_column_mapping: request.csv_column_mapping,
         sync_enabled: request.sync_enabled,
         sync_frequency_minutes: request.sync_frequency_minutes,
         sync_stock_levels: request.sync_stock_levels,
@@ -212,10 +486,7 @@ pub async fn create_connection(
             action_result: ActionResult::Success,
             event_data: serde_json::json!({
                 "connection_name": request.connection_name,
-                "erp_type": match connection.erp_type {
-                    ErpType::NetSuite => "netsuite",
-                    ErpType::SapS4Hana => "sap_s4hana",
-                }
+                "erp_type": connection.erp_type.as_str()
             }),
             ..Default::default()
         })
@@ -229,6 +500,14 @@ pub async fn create_connection(
 
 /// List all ERP connections for the authenticated user
 /// GET /api/erp/connections
+#[utoipa::path(
+    get,
+    path = "/api/erp/connections",
+    tag = "erp_integration",
+    responses(
+        (status = 200, description = "Connections of the user", body = ErpConnectionListResponse),
+    )
+)]
 pub async fn list_connections(
     State(pool): State<PgPool>,
     Extension(claims): Extension<Claims>,
@@ -255,6 +534,17 @@ pub async fn list_connections(
 
 /// Get a specific ERP connection by ID
 /// GET /api/erp/connections/:id
+#[utoipa::path(
+    get,
+    path = "/api/erp/connections/{id}",
+    tag = "erp_integration",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "The connection", body = ConnectionResponse),
+    )
+)]
 pub async fn get_connection(
     State(pool): State<PgPool>,
     Extension(claims): Extension<Claims>,
@@ -286,6 +576,17 @@ pub async fn get_connection(
 
 /// Delete an ERP connection
 /// DELETE /api/erp/connections/:id
+#[utoipa::path(
+    delete,
+    path = "/api/erp/connections/{id}",
+    tag = "erp_integration",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 204, description = "Connection deleted"),
+    )
+)]
 pub async fn delete_connection(
     State(pool): State<PgPool>,
     Extension(claims): Extension<Claims>,
@@ -333,6 +634,17 @@ pub async fn delete_connection(
 
 /// Test an ERP connection
 /// POST /api/erp/connections/:id/test
+#[utoipa::path(
+    post,
+    path = "/api/erp/connections/{id}/test",
+    tag = "erp_integration",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Test result", body = ConnectionTestResult),
+    )
+)]
 pub async fn test_connection(
     State(pool): State<PgPool>,
     Extension(claims): Extension<Claims>,
@@ -397,6 +709,19 @@ pub async fn test_connection(
 
 /// Trigger a manual sync
 /// POST /api/erp/connections/:id/sync
+#[utoipa::path(
+    post,
+    path = "/api/erp/connections/{id}/sync",
+    tag = "erp_integration",
+    operation_id = "erp_integration_trigger_sync",
+    params(
+        ("id" = Uuid, Path),
+        SyncQueryParams,
+    ),
+    responses(
+        (status = 200, description = "Sync queued", body = SyncResponse),
+    )
+)]
 pub async fn trigger_sync(
     State(pool): State<PgPool>,
     Extension(claims): Extension<Claims>,
@@ -406,7 +731,6 @@ pub async fn trigger_sync(
     tracing::info!("Triggering sync for connection {}", connection_id);
 
     let connection_service = ErpConnectionService::new(pool.clone());
-    let sync_service = ErpSyncService::new(pool.clone());
 
     // Verify connection exists and user owns it
     let connection = connection_service
@@ -420,90 +744,66 @@ pub async fn trigger_sync(
         ));
     }
 
-    // Determine sync direction and clone it for the async move block
+    // Determine sync direction
     let direction = params.direction
         .as_deref()
         .unwrap_or("bidirectional")
         .to_string();
-    let direction_clone = direction.clone();
-
-    // Spawn sync task in background (don't block the HTTP response)
-    let pool_clone = pool.clone();
-    let connection_id_clone = connection_id;
-    let user_id = claims.user_id;
-
-    tokio::spawn(async move {
-        let sync_service = ErpSyncService::new(pool_clone.clone());
-
-        let result = match direction_clone.as_str() {
-            "atlas_to_erp" => sync_service.sync_atlas_to_erp(connection_id_clone).await,
-            "erp_to_atlas" => sync_service.sync_from_erp_to_atlas(connection_id_clone).await,
-            "bidirectional" => sync_service.sync_bidirectional(connection_id_clone).await,
-            _ => {
-                tracing::error!("Invalid sync direction: {}", direction_clone);
-                return;
-            }
-        };
+    if !matches!(direction.as_str(), "atlas_to_erp" | "erp_to_atlas" | "bidirectional") {
+        return Err(AppError::BadRequest(format!("Invalid sync direction: {}", direction)));
+    }
 
-        match result {
-            Ok(sync_result) => {
-                tracing::info!(
-                    "Sync completed for connection {}: {} synced, {} failed",
-                    connection_id_clone,
-                    sync_result.items_synced,
-                    sync_result.items_failed
-                );
-
-                // Update connection metadata
-                let connection_service = ErpConnectionService::new(pool_clone.clone());
-                connection_service
-                    .update_sync_metadata(
-                        connection_id_clone,
-                        if sync_result.items_failed > 0 {
-                            "partial"
-                        } else {
-                            "success"
-                        },
-                        None,
-                    )
-                    .await
-                    .ok();
-            }
-            Err(e) => {
-                tracing::error!("Sync failed for connection {}: {}", connection_id_clone, e);
+    // Refuse up front when the field mapping template lacks a field this connection syncs
+    ErpFieldMappingService::new(pool.clone())
+        .require_complete_mapping(&connection)
+        .await?;
 
-                let connection_service = ErpConnectionService::new(pool_clone.clone());
-                connection_service
-                    .update_sync_metadata(connection_id_clone, "failed", None)
-                    .await
-                    .ok();
-            }
-        }
+    // One sync per connection at a time (shared with the scheduler)
+    let claimed = connection_service
+        .try_start_sync(connection_id)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!(e.to_string())))?;
+    if !claimed {
+        return Err(AppError::Conflict);
+    }
 
-        // Audit log
-        let audit_service = ComprehensiveAuditService::new(pool_clone);
-        audit_service
-            .log(AuditLogEntry {
-                event_type: "erp_manual_sync_completed".to_string(),
-                event_category: EventCategory::System,
-                severity: Severity::Info,
-                actor_user_id: Some(user_id),
-                actor_type: "user".to_string(),
-                resource_type: Some("erp_sync".to_string()),
-                resource_id: Some(connection_id_clone.to_string()),
-                action: "manual_sync".to_string(),
-                action_result: ActionResult::Success,
-                event_data: serde_json::json!({}),
-                ..Default::default()
-            })
+    if params.full.unwrap_or(false) {
+        connection_service
+            .set_delta_token(connection_id, None)
             .await
-            .ok();
-    });
+            .map_err(|e| AppError::Internal(anyhow::anyhow!(e.to_string())))?;
+    }
+
+    // Run the sync as a background job; it survives restarts and is retried on failure
+    let job = ErpSyncJob { connection_id, direction: direction.clone(), user_id: claims.user_id };
+    let queued = BackgroundJobService::new(pool.clone())
+        .enqueue(
+            NewJob::new(JobType::ErpSync, &job)
+                .with_dedupe_key(format!("erp_sync:{}", connection_id))
+                .created_by(claims.user_id),
+        )
+        .await;
+    let queued = match queued {
+        // A sync job of the connection is waiting to retry; it claims the connection itself
+        Ok(existing) if existing.attempts > 0 => Err(AppError::Conflict),
+        result => result,
+    };
+    let queued = match queued {
+        Ok(queued) => queued,
+        Err(e) => {
+            // Release the claim so the connection can sync again
+            let _ = connection_service
+                .finish_sync(connection_id, "failed", 0, Some("Sync could not be queued"))
+                .await;
+            return Err(e);
+        }
+    };
 
     let response = SyncResponse {
         sync_started: true,
         message: format!("Sync started for direction: {}", direction),
         sync_log_id: None,
+        job_id: Some(queued.id),
     };
 
     Ok(Json(response))
@@ -511,10 +811,24 @@ pub async fn trigger_sync(
 
 /// Get sync logs for a connection
 /// GET /api/erp/connections/:id/sync-logs
+#[utoipa::path(
+    get,
+    path = "/api/erp/connections/{id}/sync-logs",
+    tag = "erp_integration",
+    operation_id = "erp_integration_get_sync_logs",
+    params(
+        ("id" = Uuid, Path),
+        EntityFilterQuery,
+    ),
+    responses(
+        (status = 200, description = "Sync logs", body = Vec<SyncLogResponse>),
+    )
+)]
 pub async fn get_sync_logs(
     State(pool): State<PgPool>,
     Extension(claims): Extension<Claims>,
     Path(connection_id): Path<Uuid>,
+    Query(params): Query<EntityFilterQuery>,
 ) -> Result<impl IntoResponse> {
     let connection_service = ErpConnectionService::new(pool.clone());
 
@@ -537,13 +851,15 @@ pub async fn get_sync_logs(
         SELECT
             id, sync_type, sync_direction, triggered_by, status,
             items_synced, items_failed, items_skipped, duration_seconds,
-            error_message, created_at, completed_at
+            error_message, sandbox, entity_ids, created_at, completed_at
         FROM erp_sync_logs
         WHERE erp_connection_id = $1
+          AND ($2::text IS NULL OR $2 = ANY(entity_ids))
         ORDER BY created_at DESC
         LIMIT 50
         "#,
-        connection_id
+        connection_id,
+        params.entity_id
     )
     .fetch_all(&pool)
     .await
@@ -558,10 +874,23 @@ pub async fn get_sync_logs(
 
 /// Get inventory mappings for a connection
 /// GET /api/erp/connections/:id/mappings
+#[utoipa::path(
+    get,
+    path = "/api/erp/connections/{id}/mappings",
+    tag = "erp_integration",
+    params(
+        ("id" = Uuid, Path),
+        EntityFilterQuery,
+    ),
+    responses(
+        (status = 200, description = "Inventory mappings", body = Vec<MappingResponse>),
+    )
+)]
 pub async fn get_mappings(
     State(pool): State<PgPool>,
     Extension(claims): Extension<Claims>,
     Path(connection_id): Path<Uuid>,
+    Query(params): Query<EntityFilterQuery>,
 ) -> Result<impl IntoResponse> {
     let connection_service = ErpConnectionService::new(pool.clone());
 
@@ -582,13 +911,16 @@ pub async fn get_mappings(
         MappingResponse,
         r#"
         SELECT
-            id, atlas_inventory_id, erp_item_id, erp_item_name, erp_location_id,
-            sync_enabled, last_synced_at, last_sync_status
+            id, atlas_inventory_id, erp_item_id, erp_item_name, erp_location_id, erp_location_name,
+            erp_entity_id, sync_enabled, sync_direction, conflict_resolution, last_synced_at,
+            last_sync_status, erp_item_fetched_at
         FROM erp_inventory_mappings
         WHERE erp_connection_id = $1
+          AND ($2::text IS NULL OR erp_entity_id = $2)
         ORDER BY created_at DESC
         "#,
-        connection_id
+        connection_id,
+        params.entity_id
     )
     .fetch_all(&pool)
     .await
@@ -597,6 +929,264 @@ pub async fn get_mappings(
     Ok(Json(mappings))
 }
 
+/// Columns returned for a single mapping
+const MAPPING_COLUMNS: &str = "id, atlas_inventory_id, erp_item_id, erp_item_name, erp_location_id, \
+    erp_location_name, erp_entity_id, sync_enabled, sync_direction, conflict_resolution, last_synced_at, last_sync_status, \
+    erp_item_fetched_at";
+
+/// Check both sides of a mapping: the Atlas inventory belongs to the connection's owner,
+/// and the ERP item (at the location, if any) exists in the ERP
+async fn verify_mapping_targets(
+    pool: &PgPool,
+    connection_service: &ErpConnectionService,
+    connection: &ErpConnection,
+    request: &SaveMappingRequest,
+) -> Result<ErpItemDetails> {
+    let owner: Option<Uuid> = sqlx::query_scalar("SELECT user_id FROM inventory WHERE id = $1")
+        .bind(request.atlas_inventory_id)
+        .fetch_optional(pool)
+        .await?;
+    if owner != Some(connection.user_id) {
+        return Err(AppError::BadRequest(format!(
+            "Inventory {} not found",
+            request.atlas_inventory_id
+        )));
+    }
+
+    if let Some(entity_id) = &request.erp_entity_id {
+        if connection.entity(entity_id).is_none() {
+            return Err(AppError::BadRequest(format!(
+                "Entity {} is not configured on this connection",
+                entity_id
+            )));
+        }
+    }
+
+    connection_service
+        .lookup_erp_item(connection, &request.erp_item_id, request.erp_location_id.as_deref())
+        .await
+        .map_err(|e| match e {
+            crate::services::erp::erp_connection_service::ErpConnectionError::ErpItemNotFound(what) => {
+                AppError::BadRequest(format!("{} not found in the ERP", what))
+            }
+            _ => AppError::Internal(anyhow::anyhow!("Failed to fetch the ERP item: {}", e)),
+        })
+}
+
+/// Create a mapping by hand
+/// POST /api/erp/connections/:id/mappings
+#[utoipa::path(
+    post,
+    path = "/api/erp/connections/{id}/mappings",
+    tag = "erp_integration",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    request_body = SaveMappingRequest,
+    responses(
+        (status = 201, description = "Mapping created", body = MappingResponse),
+    )
+)]
+pub async fn create_mapping(
+    State(pool): State<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(connection_id): Path<Uuid>,
+    Json(request): Json<SaveMappingRequest>,
+) -> Result<impl IntoResponse> {
+    let request = request.normalize()?;
+    let connection_service = ErpConnectionService::new(pool.clone());
+
+    // Verify ownership
+    let connection = connection_service
+        .get_connection_by_id(connection_id)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!(e.to_string())))?;
+
+    if connection.user_id != claims.user_id {
+        return Err(AppError::Forbidden(
+            "You don't have permission to modify these mappings".to_string(),
+        ));
+    }
+
+    let item = verify_mapping_targets(&pool, &connection_service, &connection, &request).await?;
+
+    // Either the inventory or the ERP item (at this location) may already be mapped
+    let mapping = sqlx::query_as::<_, MappingResponse>(&format!(
+        r#"
+        INSERT INTO erp_inventory_mappings (
+            erp_connection_id, atlas_inventory_id, erp_item_id, erp_item_name, erp_location_id,
+            erp_location_name, sync_enabled, sync_direction, conflict_resolution,
+            erp_item_snapshot, erp_entity_id, erp_item_fetched_at
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, NOW())
+        ON CONFLICT DO NOTHING
+        RETURNING {}
+        "#,
+        MAPPING_COLUMNS
+    ))
+    .bind(connection_id)
+    .bind(request.atlas_inventory_id)
+    .bind(&request.erp_item_id)
+    .bind(&item.item_name)
+    .bind(&request.erp_location_id)
+    .bind(&item.location_name)
+    .bind(request.sync_enabled)
+    .bind(&request.sync_direction)
+    .bind(&request.conflict_resolution)
+    .bind(&item.snapshot)
+    .bind(&request.erp_entity_id)
+    .fetch_optional(&pool)
+    .await?
+    .ok_or(AppError::Conflict)?;
+
+    // The item may have been waiting in the webhook pending queue
+    sqlx::query(
+        r#"
+        UPDATE erp_pending_mappings
+        SET status = 'mapped', erp_mapping_id = $4, resolved_by = $5, resolved_at = NOW()
+        WHERE erp_connection_id = $1 AND erp_item_id = $2
+          AND erp_location_id IS NOT DISTINCT FROM $3 AND status = 'pending'
+        "#
+    )
+    .bind(connection_id)
+    .bind(&request.erp_item_id)
+    .bind(&request.erp_location_id)
+    .bind(mapping.id)
+    .bind(claims.user_id)
+    .execute(&pool)
+    .await?;
+
+    // Audit log
+    let audit_service = ComprehensiveAuditService::new(pool);
+    audit_service
+        .log(AuditLogEntry {
+            event_type: "erp_mapping_created".to_string(),
+            event_category: EventCategory::DataModification,
+            severity: Severity::Info,
+            actor_user_id: Some(claims.user_id),
+            actor_type: "user".to_string(),
+            resource_type: Some("erp_mapping".to_string()),
+            resource_id: Some(mapping.id.to_string()),
+            action: "create".to_string(),
+            action_result: ActionResult::Success,
+            event_data: serde_json::json!({
+                "connection_id": connection_id,
+                "atlas_inventory_id": mapping.atlas_inventory_id,
+                "erp_item_id": mapping.erp_item_id,
+                "erp_location_id": mapping.erp_location_id,
+                "erp_entity_id": mapping.erp_entity_id,
+            }),
+            ..Default::default()
+        })
+        .await
+        .ok();
+
+    Ok((StatusCode::CREATED, Json(mapping)))
+}
+
+/// Replace a mapping's targets and sync settings
+/// PUT /api/erp/mappings/:id
+#[utoipa::path(
+    put,
+    path = "/api/erp/mappings/{id}",
+    tag = "erp_integration",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    request_body = SaveMappingRequest,
+    responses(
+        (status = 200, description = "Mapping updated", body = MappingResponse),
+    )
+)]
+pub async fn update_mapping(
+    State(pool): State<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(mapping_id): Path<Uuid>,
+    Json(request): Json<SaveMappingRequest>,
+) -> Result<impl IntoResponse> {
+    let request = request.normalize()?;
+
+    let connection_id: Uuid = sqlx::query_scalar(
+        "SELECT erp_connection_id FROM erp_inventory_mappings WHERE id = $1"
+    )
+    .bind(mapping_id)
+    .fetch_optional(&pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Mapping not found".to_string()))?;
+
+    let connection_service = ErpConnectionService::new(pool.clone());
+    let connection = connection_service
+        .get_connection_by_id(connection_id)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!(e.to_string())))?;
+
+    if connection.user_id != claims.user_id {
+        return Err(AppError::Forbidden(
+            "You don't have permission to modify this mapping".to_string(),
+        ));
+    }
+
+    let item = verify_mapping_targets(&pool, &connection_service, &connection, &request).await?;
+
+    let mapping = sqlx::query_as::<_, MappingResponse>(&format!(
+        r#"
+        UPDATE erp_inventory_mappings
+        SET atlas_inventory_id = $2, erp_item_id = $3, erp_item_name = $4, erp_location_id = $5,
+            erp_location_name = $6, sync_enabled = $7, sync_direction = $8, conflict_resolution = $9,
+            erp_item_snapshot = $10, erp_entity_id = $11, erp_item_fetched_at = NOW(), updated_at = NOW()
+        WHERE id = $1
+        RETURNING {}
+        "#,
+        MAPPING_COLUMNS
+    ))
+    .bind(mapping_id)
+    .bind(request.atlas_inventory_id)
+    .bind(&request.erp_item_id)
+    .bind(&item.item_name)
+    .bind(&request.erp_location_id)
+    .bind(&item.location_name)
+    .bind(request.sync_enabled)
+    .bind(&request.sync_direction)
+    .bind(&request.conflict_resolution)
+    .bind(&item.snapshot)
+    .bind(&request.erp_entity_id)
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| match e {
+        // Another mapping already uses the inventory or the ERP item
+        sqlx::Error::Database(ref db) if db.code().as_deref() == Some("23505") => AppError::Conflict,
+        e => AppError::Database(e),
+    })?
+    .ok_or_else(|| AppError::NotFound("Mapping not found".to_string()))?;
+
+    // Audit log
+    let audit_service = ComprehensiveAuditService::new(pool);
+    audit_service
+        .log(AuditLogEntry {
+            event_type: "erp_mapping_updated".to_string(),
+            event_category: EventCategory::DataModification,
+            severity: Severity::Info,
+            actor_user_id: Some(claims.user_id),
+            actor_type: "user".to_string(),
+            resource_type: Some("erp_mapping".to_string()),
+            resource_id: Some(mapping_id.to_string()),
+            action: "update".to_string(),
+            action_result: ActionResult::Success,
+            event_data: serde_json::json!({
+                "connection_id": connection_id,
+                "atlas_inventory_id": mapping.atlas_inventory_id,
+                "erp_item_id": mapping.erp_item_id,
+                "erp_location_id": mapping.erp_location_id,
+                "erp_entity_id": mapping.erp_entity_id,
+                "sync_direction": mapping.sync_direction,
+            }),
+            ..Default::default()
+        })
+        .await
+        .ok();
+
+    Ok(Json(mapping))
+}
+
 /// Auto-discover mappings (match Atlas inventory to ERP items by NDC)
 /// POST /api/erp/connections/:id/auto-discover
 pub async fn auto_discover_mappings(
@@ -632,6 +1222,17 @@ pub async fn auto_discover_mappings(
 
 /// Delete a mapping
 /// DELETE /api/erp/mappings/:id
+#[utoipa::path(
+    delete,
+    path = "/api/erp/mappings/{id}",
+    tag = "erp_integration",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 204, description = "Mapping deleted"),
+    )
+)]
 pub async fn delete_mapping(
     State(pool): State<PgPool>,
     Extension(claims): Extension<Claims>,
@@ -666,45 +1267,1310 @@ pub async fn delete_mapping(
     Ok(StatusCode::NO_CONTENT)
 }
 
-// ============================================================================
-// Webhook Handlers (for real-time ERP updates)
-// ============================================================================
-
-/// NetSuite webhook endpoint (SECURED with HMAC signature verification)
-/// POST /api/erp/webhooks/netsuite/:connection_id
-///
-/// **Security:**
-/// - HMAC-SHA256 signature verification required
-/// - Rate limiting: 100 requests per 15 minutes per connection
-/// - Connection must exist and have webhooks enabled
-/// - All attempts logged to audit table
-/// - IP address tracking
-/// - Payload size limit: 1MB
-///
-/// **Headers Required:**
-/// - X-Webhook-Signature: sha256=<hex_signature>
-/// - Content-Type: application/json
-///
-/// **Rate Limit Headers (Response):**
-/// - X-RateLimit-Remaining: requests remaining in window
-/// - X-RateLimit-Reset: timestamp when window resets
-pub async fn netsuite_webhook(
+/// ERP items reported by webhooks that have no mapping yet, most recently seen first
+/// GET /api/erp/connections/:id/pending-mappings?status=
+#[utoipa::path(
+    get,
+    path = "/api/erp/connections/{id}/pending-mappings",
+    tag = "erp_integration",
+    params(
+        ("id" = Uuid, Path),
+        PendingMappingQuery,
+    ),
+    responses(
+        (status = 200, description = "ERP items awaiting a mapping", body = Vec<PendingMappingResponse>),
+    )
+)]
+pub async fn get_pending_mappings(
     State(pool): State<PgPool>,
+    Extension(claims): Extension<Claims>,
     Path(connection_id): Path<Uuid>,
-    headers: HeaderMap,
-    body: Bytes,
+    Query(params): Query<PendingMappingQuery>,
 ) -> Result<impl IntoResponse> {
-    let start_time = std::time::Instant::now();
-    let request_id = Uuid::new_v4();
+    let connection_service = ErpConnectionService::new(pool.clone());
 
-    // Extract source IP (if behind proxy, use X-Forwarded-For)
-    let source_ip = headers
-        .get("x-forwarded-for")
-        .and_then(|v| v.to_str().ok())
-        .or_else(|| headers.get("x-real-ip").and_then(|v| v.to_str().ok()))
-        .map(|s| s.to_string());
+    // Verify ownership
+    let connection = connection_service
+        .get_connection_by_id(connection_id)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!(e.to_string())))?;
 
-    // Initialize security service
+    if connection.user_id != claims.user_id {
+        return Err(AppError::Forbidden(
+            "You don't have permission to view these mappings".to_string(),
+        ));
+    }
+
+    let status = params.status.as_deref().unwrap_or("pending");
+    if !["pending", "mapped", "ignored", "all"].contains(&status) {
+        return Err(AppError::BadRequest(
+            "Invalid status. Must be 'pending', 'mapped', 'ignored' or 'all'".to_string(),
+        ));
+    }
+
+    let pending = sqlx::query_as::<_, PendingMappingResponse>(
+        r#"
+        SELECT
+            id, erp_item_id, erp_location_id, erp_item_name, ndc_code, last_quantity,
+            last_event_type, occurrences, status, erp_mapping_id, first_seen_at, last_seen_at
+        FROM erp_pending_mappings
+        WHERE erp_connection_id = $1 AND ($2 = 'all' OR status = $2)
+        ORDER BY last_seen_at DESC
+        LIMIT 500
+        "#
+    )
+    .bind(connection_id)
+    .bind(status)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| AppError::Internal(anyhow::anyhow!(e.to_string())))?;
+
+    Ok(Json(pending))
+}
+
+/// Map a pending ERP item to Atlas inventory, or ignore it
+/// POST /api/erp/connections/:id/pending-mappings/:pending_id/resolve
+#[utoipa::path(
+    post,
+    path = "/api/erp/connections/{id}/pending-mappings/{pending_id}/resolve",
+    tag = "erp_integration",
+    params(
+        ("id" = Uuid, Path),
+        ("pending_id" = Uuid, Path),
+    ),
+    request_body = ResolvePendingMappingRequest,
+    responses(
+        (status = 200, description = "The pending mapping, mapped or ignored", body = serde_json::Value),
+    )
+)]
+pub async fn resolve_pending_mapping(
+    State(pool): State<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path((connection_id, pending_id)): Path<(Uuid, Uuid)>,
+    Json(request): Json<ResolvePendingMappingRequest>,
+) -> Result<impl IntoResponse> {
+    let connection_service = ErpConnectionService::new(pool.clone());
+
+    // Verify ownership
+    let connection = connection_service
+        .get_connection_by_id(connection_id)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!(e.to_string())))?;
+
+    if connection.user_id != claims.user_id {
+        return Err(AppError::Forbidden(
+            "You don't have permission to modify these mappings".to_string(),
+        ));
+    }
+
+    let pending: Option<(String, Option<String>, Option<String>)> = sqlx::query_as(
+        r#"
+        SELECT erp_item_id, erp_location_id, erp_item_name
+        FROM erp_pending_mappings
+        WHERE id = $1 AND erp_connection_id = $2 AND status = 'pending'
+        "#
+    )
+    .bind(pending_id)
+    .bind(connection_id)
+    .fetch_optional(&pool)
+    .await?;
+
+    let (erp_item_id, erp_location_id, erp_item_name) = pending
+        .ok_or_else(|| AppError::NotFound("Pending mapping not found".to_string()))?;
+
+    let mapping_id = match request.action.as_str() {
+        "map" => {
+            let inventory_id = request.atlas_inventory_id
+                .ok_or_else(|| AppError::BadRequest("atlas_inventory_id is required to map an item".to_string()))?;
+
+            let owner: Option<Uuid> = sqlx::query_scalar("SELECT user_id FROM inventory WHERE id = $1")
+                .bind(inventory_id)
+                .fetch_optional(&pool)
+                .await?;
+            if owner != Some(connection.user_id) {
+                return Err(AppError::NotFound("Inventory not found".to_string()));
+            }
+
+            let mut tx = pool.begin().await?;
+
+            // Either the inventory or the ERP item (at this location) may already be mapped
+            let mapping_id: Uuid = sqlx::query_scalar(
+                r#"
+                INSERT INTO erp_inventory_mappings (
+                    erp_connection_id, atlas_inventory_id, erp_item_id, erp_item_name, erp_location_id
+                ) VALUES ($1, $2, $3, $4, $5)
+                ON CONFLICT DO NOTHING
+                RETURNING id
+                "#
+            )
+            .bind(connection_id)
+            .bind(inventory_id)
+            .bind(&erp_item_id)
+            .bind(&erp_item_name)
+            .bind(&erp_location_id)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or(AppError::Conflict)?;
+
+            sqlx::query(
+                r#"
+                UPDATE erp_pending_mappings
+                SET status = 'mapped', erp_mapping_id = $2, resolved_by = $3, resolved_at = NOW()
+                WHERE id = $1
+                "#
+            )
+            .bind(pending_id)
+            .bind(mapping_id)
+            .bind(claims.user_id)
+            .execute(&mut *tx)
+            .await?;
+
+            tx.commit().await?;
+            Some(mapping_id)
+        }
+        "ignore" => {
+            sqlx::query(
+                r#"
+                UPDATE erp_pending_mappings
+                SET status = 'ignored', resolved_by = $2, resolved_at = NOW()
+                WHERE id = $1
+                "#
+            )
+            .bind(pending_id)
+            .bind(claims.user_id)
+            .execute(&pool)
+            .await?;
+            None
+        }
+        _ => {
+            return Err(AppError::BadRequest(
+                "Invalid action. Must be 'map' or 'ignore'".to_string(),
+            ));
+        }
+    };
+
+    // Audit log
+    let audit_service = ComprehensiveAuditService::new(pool);
+    audit_service
+        .log(AuditLogEntry {
+            event_type: "erp_pending_mapping_resolved".to_string(),
+            event_category: EventCategory::DataModification,
+            severity: Severity::Info,
+            actor_user_id: Some(claims.user_id),
+            actor_type: "user".to_string(),
+            resource_type: Some("erp_pending_mapping".to_string()),
+            resource_id: Some(pending_id.to_string()),
+            action: request.action.clone(),
+            action_result: ActionResult::Success,
+            event_data: serde_json::json!({
+                "connection_id": connection_id,
+                "erp_item_id": erp_item_id,
+                "erp_mapping_id": mapping_id,
+            }),
+            ..Default::default()
+        })
+        .await
+        .ok();
+
+    Ok(Json(serde_json::json!({
+        "id": pending_id,
+        "status": if mapping_id.is_some() { "mapped" } else { "ignored" },
+        "erp_mapping_id": mapping_id,
+    })))
+}
+
+// ============================================================================
+// Flat-File Connection Handlers
+// ============================================================================
+
+/// Load a flat-file connection owned by the user
+async fn get_owned_flat_file_connection(
+    service: &ErpConnectionService,
+    connection_id: Uuid,
+    user_id: Uuid,
+) -> Result<ErpConnection> {
+    let connection = service
+        .get_connection_by_id(connection_id)
+        .await
+        .map_err(|e| match e {
+            crate::services::erp::erp_connection_service::ErpConnectionError::NotFound(_) => {
+                AppError::NotFound(format!("Connection {} not found", connection_id))
+            }
+            _ => AppError::Internal(anyhow::anyhow!(e.to_string())),
+        })?;
+
+    if connection.user_id != user_id {
+        return Err(AppError::Forbidden(
+            "You don't have permission to access this connection".to_string(),
+        ));
+    }
+
+    if connection.erp_type != ErpType::FlatFile {
+        return Err(AppError::BadRequest(
+            "Column mappings only apply to flat_file connections".to_string(),
+        ));
+    }
+
+    Ok(connection)
+}
+
+/// Get the CSV column mapping and the fields it can map
+/// GET /api/erp/connections/:id/column-mapping
+#[utoipa::path(
+    get,
+    path = "/api/erp/connections/{id}/column-mapping",
+    tag = "erp_integration",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Column mapping of the flat-file connection", body = ColumnMappingResponse),
+    )
+)]
+pub async fn get_column_mapping(
+    State(pool): State<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(connection_id): Path<Uuid>,
+) -> Result<impl IntoResponse> {
+    let service = ErpConnectionService::new(pool);
+    let connection = get_owned_flat_file_connection(&service, connection_id, claims.user_id).await?;
+
+    Ok(Json(ColumnMappingResponse {
+        mapping: connection.column_mapping.unwrap_or_default(),
+        fields: FLAT_FILE_FIELDS,
+    }))
+}
+
+/// Replace the CSV column mapping
+/// PUT /api/erp/connections/:id/column-mapping
+#[utoipa::path(
+    put,
+    path = "/api/erp/connections/{id}/column-mapping",
+    tag = "erp_integration",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    request_body = FlatFileColumnMapping,
+    responses(
+        (status = 200, description = "Column mapping of the flat-file connection", body = ColumnMappingResponse),
+    )
+)]
+pub async fn update_column_mapping(
+    State(pool): State<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(connection_id): Path<Uuid>,
+    Json(mapping): Json<FlatFileColumnMapping>,
+) -> Result<impl IntoResponse> {
+    let service = ErpConnectionService::new(pool.clone());
+    get_owned_flat_file_connection(&service, connection_id, claims.user_id).await?;
+
+    service
+        .update_column_mapping(connection_id, &mapping)
+        .await
+        .map_err(|e| match e {
+            crate::services::erp::erp_connection_service::ErpConnectionError::ConfigError(msg) => {
+                AppError::BadRequest(msg)
+            }
+            _ => AppError::Internal(anyhow::anyhow!(e.to_string())),
+        })?;
+
+    // Audit log
+    let audit_service = ComprehensiveAuditService::new(pool);
+    audit_service
+        .log(AuditLogEntry {
+            event_type: "erp_column_mapping_updated".to_string(),
+            event_category: EventCategory::DataModification,
+            severity: Severity::Info,
+            actor_user_id: Some(claims.user_id),
+            actor_type: "user".to_string(),
+            resource_type: Some("erp_connection".to_string()),
+            resource_id: Some(connection_id.to_string()),
+            action: "update".to_string(),
+            action_result: ActionResult::Success,
+            event_data: serde_json::json!({ "column_mapping": mapping }),
+            ..Default::default()
+        })
+        .await
+        .ok();
+
+    Ok(Json(ColumnMappingResponse {
+        mapping,
+        fields: FLAT_FILE_FIELDS,
+    }))
+}
+
+/// Parse a sample file with a column mapping, without importing anything
+/// POST /api/erp/connections/:id/column-mapping/preview
+#[utoipa::path(
+    post,
+    path = "/api/erp/connections/{id}/column-mapping/preview",
+    tag = "erp_integration",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    request_body = ColumnMappingPreviewRequest,
+    responses(
+        (status = 200, description = "The sample file parsed with the mapping", body = ColumnMappingPreviewResponse),
+    )
+)]
+pub async fn preview_column_mapping(
+    State(pool): State<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(connection_id): Path<Uuid>,
+    Json(request): Json<ColumnMappingPreviewRequest>,
+) -> Result<impl IntoResponse> {
+    let service = ErpConnectionService::new(pool);
+    let connection = get_owned_flat_file_connection(&service, connection_id, claims.user_id).await?;
+
+    let mapping = match request.mapping {
+        Some(mapping) => {
+            mapping.validate().map_err(AppError::BadRequest)?;
+            mapping
+        }
+        None => connection.column_mapping.clone().unwrap_or_default(),
+    };
+
+    let (file_name, contents) = match request.sample_csv {
+        Some(sample) => (None, sample.into_bytes()),
+        None => {
+            let config = connection.flat_file_config.clone()
+                .ok_or_else(|| AppError::Internal(anyhow::anyhow!("SFTP config not loaded")))?;
+            let client = SftpClient::new(config)
+                .map_err(|e| AppError::BadRequest(e.to_string()))?;
+
+            let files = client.list_inbound().await
+                .map_err(|e| AppError::BadRequest(format!("SFTP error: {}", e)))?;
+            let newest = files.last()
+                .ok_or_else(|| AppError::NotFound("No files in the inbound directory; provide sample_csv".to_string()))?;
+            let contents = client.download(&newest.path).await
+                .map_err(|e| AppError::BadRequest(format!("SFTP error: {}", e)))?;
+
+            (Some(newest.name.clone()), contents)
+        }
+    };
+
+    let response = match parse_csv(&contents, &mapping) {
+        Ok(parsed) => {
+            let mapped: Vec<&str> = mapping.columns().into_iter().map(|(_, column)| column).collect();
+            let unmapped_headers = parsed.headers
+                .iter()
+                .filter(|h| !mapped.iter().any(|c| c.trim().eq_ignore_ascii_case(h)))
+                .cloned()
+                .collect();
+
+            ColumnMappingPreviewResponse {
+                file_name,
+                unmapped_headers,
+                headers: parsed.headers,
+                total_rows: parsed.rows.len(),
+                rows: parsed.rows.into_iter().take(PREVIEW_ROWS).collect(),
+                error_count: parsed.errors.len(),
+                errors: parsed.errors.into_iter().take(PREVIEW_ERRORS).collect(),
+                file_error: None,
+            }
+        }
+        Err(file_error) => ColumnMappingPreviewResponse {
+            file_name,
+            headers: Vec::new(),
+            unmapped_headers: Vec::new(),
+            total_rows: 0,
+            rows: Vec::new(),
+            error_count: 0,
+            errors: Vec::new(),
+            file_error: Some(file_error),
+        },
+    };
+
+    Ok(Json(response))
+}
+
+/// Files pulled from and pushed to the SFTP server, newest first
+/// GET /api/erp/connections/:id/file-transfers
+#[utoipa::path(
+    get,
+    path = "/api/erp/connections/{id}/file-transfers",
+    tag = "erp_integration",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "File transfers", body = Vec<FlatFileTransferResponse>),
+    )
+)]
+pub async fn get_file_transfers(
+    State(pool): State<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(connection_id): Path<Uuid>,
+) -> Result<impl IntoResponse> {
+    let service = ErpConnectionService::new(pool.clone());
+    get_owned_flat_file_connection(&service, connection_id, claims.user_id).await?;
+
+    let transfers = sqlx::query_as::<_, FlatFileTransferResponse>(
+        r#"
+        SELECT
+            id, sync_log_id, direction, file_name, file_size, checksum_sha256, status,
+            rows_total, rows_failed, error_message, created_at
+        FROM erp_flat_file_transfers
+        WHERE erp_connection_id = $1
+        ORDER BY created_at DESC
+        LIMIT 100
+        "#
+    )
+    .bind(connection_id)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| AppError::Internal(anyhow::anyhow!(e.to_string())))?;
+
+    Ok(Json(transfers))
+}
+
+// ============================================================================
+// Subsidiary / Plant Handlers
+// ============================================================================
+
+/// Subsidiaries (NetSuite) or plants (SAP) synced by a connection
+/// GET /api/erp/connections/:id/entities
+#[utoipa::path(
+    get,
+    path = "/api/erp/connections/{id}/entities",
+    tag = "erp_integration",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Entities synced by the connection", body = Vec<ErpEntity>),
+    )
+)]
+pub async fn get_connection_entities(
+    State(pool): State<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(connection_id): Path<Uuid>,
+) -> Result<impl IntoResponse> {
+    let connection = get_owned_connection(&pool, connection_id, claims.user_id).await?;
+
+    Ok(Json(connection.entities))
+}
+
+/// Replace the subsidiaries / plants of a connection and their Atlas storage locations
+/// PUT /api/erp/connections/:id/entities
+#[utoipa::path(
+    put,
+    path = "/api/erp/connections/{id}/entities",
+    tag = "erp_integration",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    request_body = ReplaceEntitiesRequest,
+    responses(
+        (status = 200, description = "Entities synced by the connection", body = Vec<ErpEntity>),
+    )
+)]
+pub async fn replace_connection_entities(
+    State(pool): State<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(connection_id): Path<Uuid>,
+    Json(request): Json<ReplaceEntitiesRequest>,
+) -> Result<impl IntoResponse> {
+    let connection = get_owned_connection(&pool, connection_id, claims.user_id).await?;
+
+    let service = ErpConnectionService::new(pool.clone());
+    let entities: Vec<ErpEntity> = service
+        .replace_entities(&connection, request.entities)
+        .await
+        .map_err(|e| match e {
+            crate::services::erp::erp_connection_service::ErpConnectionError::ConfigError(msg) => {
+                AppError::BadRequest(msg)
+            }
+            crate::services::erp::erp_connection_service::ErpConnectionError::DatabaseError(e) => {
+                AppError::Database(e)
+            }
+            e => AppError::Internal(anyhow::anyhow!(e.to_string())),
+        })?;
+
+    // Audit log
+    let audit_service = ComprehensiveAuditService::new(pool);
+    audit_service
+        .log(AuditLogEntry {
+            event_type: "erp_entities_updated".to_string(),
+            event_category: EventCategory::DataModification,
+            severity: Severity::Info,
+            actor_user_id: Some(claims.user_id),
+            actor_type: "user".to_string(),
+            resource_type: Some("erp_connection".to_string()),
+            resource_id: Some(connection_id.to_string()),
+            resource_name: Some(connection.connection_name.clone()),
+            action: "update".to_string(),
+            action_result: ActionResult::Success,
+            old_values: Some(serde_json::json!({ "entities": connection.entities })),
+            new_values: Some(serde_json::json!({ "entities": entities })),
+            ..Default::default()
+        })
+        .await
+        .ok();
+
+    Ok(Json(entities))
+}
+
+// ============================================================================
+// Sandbox Mode Handlers
+// ============================================================================
+
+/// Turn sandbox mode on or off: sandbox syncs capture ERP writes instead of executing them
+/// PUT /api/erp/connections/:id/sandbox
+#[utoipa::path(
+    put,
+    path = "/api/erp/connections/{id}/sandbox",
+    tag = "erp_integration",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    request_body = SandboxModeRequest,
+    responses(
+        (status = 200, description = "The connection", body = ConnectionResponse),
+    )
+)]
+pub async fn set_sandbox_mode(
+    State(pool): State<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(connection_id): Path<Uuid>,
+    Json(request): Json<SandboxModeRequest>,
+) -> Result<impl IntoResponse> {
+    let connection = get_owned_connection(&pool, connection_id, claims.user_id).await?;
+
+    let service = ErpConnectionService::new(pool.clone());
+    service
+        .set_sandbox_mode(connection_id, request.enabled)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!(e.to_string())))?;
+
+    // Audit log
+    let audit_service = ComprehensiveAuditService::new(pool.clone());
+    audit_service
+        .log(AuditLogEntry {
+            event_type: if request.enabled { "erp_sandbox_enabled" } else { "erp_sandbox_disabled" }.to_string(),
+            event_category: EventCategory::DataModification,
+            severity: if request.enabled { Severity::Info } else { Severity::Warning },
+            actor_user_id: Some(claims.user_id),
+            actor_type: "user".to_string(),
+            resource_type: Some("erp_connection".to_string()),
+            resource_id: Some(connection_id.to_string()),
+            resource_name: Some(connection.connection_name.clone()),
+            action: "update".to_string(),
+            action_result: ActionResult::Success,
+            old_values: Some(serde_json::json!({ "sandbox_mode": connection.sandbox_mode })),
+            new_values: Some(serde_json::json!({ "sandbox_mode": request.enabled })),
+            ..Default::default()
+        })
+        .await
+        .ok();
+
+    let updated = get_owned_connection(&pool, connection_id, claims.user_id).await?;
+    Ok(Json(service.to_response(&updated)))
+}
+
+/// ERP writes captured in sandbox mode, newest first
+/// GET /api/erp/connections/:id/sandbox-writes?sync_log_id=
+#[utoipa::path(
+    get,
+    path = "/api/erp/connections/{id}/sandbox-writes",
+    tag = "erp_integration",
+    params(
+        ("id" = Uuid, Path),
+        SandboxWriteQuery,
+    ),
+    responses(
+        (status = 200, description = "Writes recorded in sandbox mode", body = Vec<SandboxWriteResponse>),
+    )
+)]
+pub async fn get_sandbox_writes(
+    State(pool): State<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(connection_id): Path<Uuid>,
+    Query(params): Query<SandboxWriteQuery>,
+) -> Result<impl IntoResponse> {
+    get_owned_connection(&pool, connection_id, claims.user_id).await?;
+
+    let writes = sqlx::query_as::<_, SandboxWriteResponse>(
+        r#"
+        SELECT
+            id, erp_sync_log_id, erp_mapping_id, erp_item_id, erp_location_id, operation,
+            current_value, proposed_value,
+            current_value IS DISTINCT FROM proposed_value AS changed,
+            created_at
+        FROM erp_sandbox_writes
+        WHERE erp_connection_id = $1
+          AND ($2::uuid IS NULL OR erp_sync_log_id = $2)
+        ORDER BY created_at DESC
+        LIMIT 500
+        "#
+    )
+    .bind(connection_id)
+    .bind(params.sync_log_id)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| AppError::Internal(anyhow::anyhow!(e.to_string())))?;
+
+    Ok(Json(writes))
+}
+
+// ============================================================================
+// Field Mapping Template Handlers
+// ============================================================================
+
+/// List the system templates, shared templates and the user's own
+/// GET /api/erp/field-mapping-templates?erp_type=netsuite
+#[utoipa::path(
+    get,
+    path = "/api/erp/field-mapping-templates",
+    tag = "erp_integration",
+    params(
+        FieldMappingTemplateQuery,
+    ),
+    responses(
+        (status = 200, description = "Field mapping templates", body = Vec<FieldMappingTemplate>),
+    )
+)]
+pub async fn list_field_mapping_templates(
+    State(pool): State<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Query(params): Query<FieldMappingTemplateQuery>,
+) -> Result<impl IntoResponse> {
+    let templates = ErpFieldMappingService::new(pool)
+        .list_templates(claims.user_id, params.erp_type.as_deref())
+        .await?;
+
+    Ok(Json(templates))
+}
+
+/// Create a field mapping template
+/// POST /api/erp/field-mapping-templates
+#[utoipa::path(
+    post,
+    path = "/api/erp/field-mapping-templates",
+    tag = "erp_integration",
+    request_body = SaveFieldMappingTemplateRequest,
+    responses(
+        (status = 201, description = "Template created", body = FieldMappingTemplate),
+    )
+)]
+pub async fn create_field_mapping_template(
+    State(pool): State<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<SaveFieldMappingTemplateRequest>,
+) -> Result<impl IntoResponse> {
+    let template = ErpFieldMappingService::new(pool.clone())
+        .create_template(claims.user_id, request)
+        .await?;
+
+    log_field_mapping_event(&pool, &claims, "erp_field_mapping_template_created", "create", &template).await;
+
+    Ok((StatusCode::CREATED, Json(template)))
+}
+
+/// Edit one of the user's templates
+/// PUT /api/erp/field-mapping-templates/:id
+#[utoipa::path(
+    put,
+    path = "/api/erp/field-mapping-templates/{id}",
+    tag = "erp_integration",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    request_body = SaveFieldMappingTemplateRequest,
+    responses(
+        (status = 200, description = "Template updated", body = FieldMappingTemplate),
+    )
+)]
+pub async fn update_field_mapping_template(
+    State(pool): State<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(template_id): Path<Uuid>,
+    Json(request): Json<SaveFieldMappingTemplateRequest>,
+) -> Result<impl IntoResponse> {
+    let template = ErpFieldMappingService::new(pool.clone())
+        .update_template(template_id, claims.user_id, request)
+        .await?;
+
+    log_field_mapping_event(&pool, &claims, "erp_field_mapping_template_updated", "update", &template).await;
+
+    Ok(Json(template))
+}
+
+/// Delete one of the user's templates
+/// DELETE /api/erp/field-mapping-templates/:id
+#[utoipa::path(
+    delete,
+    path = "/api/erp/field-mapping-templates/{id}",
+    tag = "erp_integration",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 204, description = "Template deleted"),
+    )
+)]
+pub async fn delete_field_mapping_template(
+    State(pool): State<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(template_id): Path<Uuid>,
+) -> Result<impl IntoResponse> {
+    ErpFieldMappingService::new(pool.clone())
+        .delete_template(template_id, claims.user_id)
+        .await?;
+
+    let audit_service = ComprehensiveAuditService::new(pool);
+    audit_service
+        .log(AuditLogEntry {
+            event_type: "erp_field_mapping_template_deleted".to_string(),
+            event_category: EventCategory::DataModification,
+            severity: Severity::Info,
+            actor_user_id: Some(claims.user_id),
+            actor_type: "user".to_string(),
+            resource_type: Some("erp_field_mapping_template".to_string()),
+            resource_id: Some(template_id.to_string()),
+            action: "delete".to_string(),
+            action_result: ActionResult::Success,
+            ..Default::default()
+        })
+        .await
+        .ok();
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn log_field_mapping_event(
+    pool: &PgPool,
+    claims: &Claims,
+    event_type: &str,
+    action: &str,
+    template: &FieldMappingTemplate,
+) {
+    let audit_service = ComprehensiveAuditService::new(pool.clone());
+    audit_service
+        .log(AuditLogEntry {
+            event_type: event_type.to_string(),
+            event_category: EventCategory::DataModification,
+            severity: Severity::Info,
+            actor_user_id: Some(claims.user_id),
+            actor_type: "user".to_string(),
+            resource_type: Some("erp_field_mapping_template".to_string()),
+            resource_id: Some(template.id.to_string()),
+            action: action.to_string(),
+            action_result: ActionResult::Success,
+            event_data: serde_json::json!({
+                "erp_type": template.erp_type,
+                "template_name": template.template_name,
+                "field_mappings": template.field_mappings.0,
+            }),
+            ..Default::default()
+        })
+        .await
+        .ok();
+}
+
+/// Load a connection owned by the user
+async fn get_owned_connection(pool: &PgPool, connection_id: Uuid, user_id: Uuid) -> Result<ErpConnection> {
+    let connection = ErpConnectionService::new(pool.clone())
+        .get_connection_by_id(connection_id)
+        .await
+        .map_err(|e| match e {
+            crate::services::erp::erp_connection_service::ErpConnectionError::NotFound(_) => {
+                AppError::NotFound(format!("Connection {} not found", connection_id))
+            }
+            _ => AppError::Internal(anyhow::anyhow!(e.to_string())),
+        })?;
+
+    if connection.user_id != user_id {
+        return Err(AppError::Forbidden(
+            "You don't have permission to access this connection".to_string(),
+        ));
+    }
+
+    Ok(connection)
+}
+
+/// The connection's field mapping template and any fields it lacks for syncing
+/// GET /api/erp/connections/:id/field-mapping
+#[utoipa::path(
+    get,
+    path = "/api/erp/connections/{id}/field-mapping",
+    tag = "erp_integration",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Field mapping of the connection", body = crate::services::erp::erp_field_mapping_service::ConnectionFieldMapping),
+    )
+)]
+pub async fn get_connection_field_mapping(
+    State(pool): State<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(connection_id): Path<Uuid>,
+) -> Result<impl IntoResponse> {
+    let connection = get_owned_connection(&pool, connection_id, claims.user_id).await?;
+
+    let field_mapping = ErpFieldMappingService::new(pool)
+        .describe_connection(&connection)
+        .await?;
+
+    Ok(Json(field_mapping))
+}
+
+/// Assign a field mapping template to a connection
+/// PUT /api/erp/connections/:id/field-mapping
+#[utoipa::path(
+    put,
+    path = "/api/erp/connections/{id}/field-mapping",
+    tag = "erp_integration",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    request_body = AssignFieldMappingRequest,
+    responses(
+        (status = 200, description = "Field mapping of the connection", body = crate::services::erp::erp_field_mapping_service::ConnectionFieldMapping),
+    )
+)]
+pub async fn assign_connection_field_mapping(
+    State(pool): State<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(connection_id): Path<Uuid>,
+    Json(request): Json<AssignFieldMappingRequest>,
+) -> Result<impl IntoResponse> {
+    let connection = get_owned_connection(&pool, connection_id, claims.user_id).await?;
+
+    let field_mapping = ErpFieldMappingService::new(pool.clone())
+        .assign_template(&connection, request.template_id)
+        .await?;
+
+    let audit_service = ComprehensiveAuditService::new(pool);
+    audit_service
+        .log(AuditLogEntry {
+            event_type: "erp_field_mapping_assigned".to_string(),
+            event_category: EventCategory::DataModification,
+            severity: Severity::Info,
+            actor_user_id: Some(claims.user_id),
+            actor_type: "user".to_string(),
+            resource_type: Some("erp_connection".to_string()),
+            resource_id: Some(connection_id.to_string()),
+            action: "update".to_string(),
+            action_result: ActionResult::Success,
+            event_data: serde_json::json!({ "template_id": request.template_id }),
+            ..Default::default()
+        })
+        .await
+        .ok();
+
+    Ok(Json(field_mapping))
+}
+
+// ============================================================================
+// ERP Item Catalog Handlers
+// ============================================================================
+
+/// Search the connection's cached ERP item catalog (fetched from the ERP on first use or
+/// with `refresh=true`)
+/// GET /api/erp/connections/:id/items?query=&limit=&offset=&refresh=
+#[utoipa::path(
+    get,
+    path = "/api/erp/connections/{id}/items",
+    tag = "erp_integration",
+    params(
+        ("id" = Uuid, Path),
+        CatalogQuery,
+    ),
+    responses(
+        (status = 200, description = "A page of ERP items", body = crate::services::erp::erp_item_catalog_service::CatalogPage),
+    )
+)]
+pub async fn browse_erp_items(
+    State(pool): State<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(connection_id): Path<Uuid>,
+    Query(params): Query<CatalogQuery>,
+) -> Result<impl IntoResponse> {
+    let connection = get_owned_connection(&pool, connection_id, claims.user_id).await?;
+
+    let page = ErpItemCatalogService::new(pool)
+        .search(&connection, &params)
+        .await?;
+
+    Ok(Json(page))
+}
+
+// ============================================================================
+// Conflict Review Handlers
+// ============================================================================
+
+/// Sync conflicts of a connection with the latest AI suggestion for each
+/// GET /api/erp/connections/:id/conflicts?status=pending
+#[utoipa::path(
+    get,
+    path = "/api/erp/connections/{id}/conflicts",
+    tag = "erp_integration",
+    params(
+        ("id" = Uuid, Path),
+        ConflictQuery,
+    ),
+    responses(
+        (status = 200, description = "Sync conflicts", body = Vec<crate::services::erp::erp_conflict_service::ConflictQueueItem>),
+    )
+)]
+pub async fn list_conflicts(
+    State(pool): State<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(connection_id): Path<Uuid>,
+    Query(params): Query<ConflictQuery>,
+) -> Result<impl IntoResponse> {
+    get_owned_connection(&pool, connection_id, claims.user_id).await?;
+
+    let status = match params.status.as_deref().unwrap_or("pending") {
+        "all" => None,
+        status @ ("pending" | "resolved" | "ignored" | "auto_resolved") => Some(status),
+        other => return Err(AppError::BadRequest(format!("Invalid status filter: {}", other))),
+    };
+
+    let conflicts = ErpConflictService::new(pool)
+        .list_conflicts(connection_id, status)
+        .await?;
+
+    Ok(Json(conflicts))
+}
+
+/// Apply the chosen side of a conflict (use_atlas, use_erp or ignore)
+/// POST /api/erp/conflicts/:id/resolve
+#[utoipa::path(
+    post,
+    path = "/api/erp/conflicts/{id}/resolve",
+    tag = "erp_integration",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    request_body = ResolveConflictRequest,
+    responses(
+        (status = 200, description = "Conflict resolved", body = ConflictResolutionOutcome),
+    )
+)]
+pub async fn resolve_conflict(
+    State(pool): State<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(conflict_id): Path<Uuid>,
+    Json(request): Json<ResolveConflictRequest>,
+) -> Result<impl IntoResponse> {
+    let outcome = ErpConflictService::new(pool.clone())
+        .resolve_conflict(conflict_id, claims.user_id, &request)
+        .await?;
+
+    log_conflict_event(&pool, &claims, "erp_conflict_resolved", &request.action, &outcome).await;
+
+    Ok(Json(outcome))
+}
+
+/// Undo a resolution within the undo window, restoring the replaced quantities
+/// POST /api/erp/conflicts/:id/undo
+#[utoipa::path(
+    post,
+    path = "/api/erp/conflicts/{id}/undo",
+    tag = "erp_integration",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Resolution undone", body = ConflictResolutionOutcome),
+    )
+)]
+pub async fn undo_conflict_resolution(
+    State(pool): State<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(conflict_id): Path<Uuid>,
+) -> Result<impl IntoResponse> {
+    let outcome = ErpConflictService::new(pool.clone())
+        .undo_resolution(conflict_id, claims.user_id)
+        .await?;
+
+    log_conflict_event(&pool, &claims, "erp_conflict_resolution_undone", "undo", &outcome).await;
+
+    Ok(Json(outcome))
+}
+
+async fn log_conflict_event(
+    pool: &PgPool,
+    claims: &Claims,
+    event_type: &str,
+    action: &str,
+    outcome: &ConflictResolutionOutcome,
+) {
+    let conflict = &outcome.conflict;
+    let values = |side: fn(&(i32, i32)) -> i32| {
+        serde_json::json!({
+            "atlas_quantity": outcome.atlas_quantity.as_ref().map(side),
+            "erp_quantity": outcome.erp_quantity.as_ref().map(side),
+        })
+    };
+
+    let audit_service = ComprehensiveAuditService::new(pool.clone());
+    audit_service
+        .log(AuditLogEntry {
+            event_type: event_type.to_string(),
+            event_category: EventCategory::DataModification,
+            severity: Severity::Info,
+            actor_user_id: Some(claims.user_id),
+            actor_type: "user".to_string(),
+            actor_identifier: Some(claims.email.clone()),
+            resource_type: Some("erp_conflict".to_string()),
+            resource_id: Some(conflict.id.to_string()),
+            action: action.to_string(),
+            action_result: ActionResult::Success,
+            event_data: serde_json::json!({
+                "erp_connection_id": conflict.erp_connection_id,
+                "erp_mapping_id": conflict.erp_mapping_id,
+                "atlas_inventory_id": conflict.atlas_inventory_id,
+                "erp_item_id": conflict.erp_item_id,
+                "conflict_type": conflict.conflict_type,
+                "ai_suggested_resolution": conflict.ai_suggested_resolution,
+            }),
+            old_values: Some(values(|(before, _)| *before)),
+            new_values: Some(values(|(_, after)| *after)),
+            compliance_tags: vec!["erp_integration".to_string()],
+            ..Default::default()
+        })
+        .await
+        .ok();
+}
+
+// ============================================================================
+// Marketplace Order Export Handlers
+// ============================================================================
+
+/// Configure sales/purchase order export for completed marketplace transactions
+/// PUT /api/erp/connections/:id/order-export
+#[utoipa::path(
+    put,
+    path = "/api/erp/connections/{id}/order-export",
+    tag = "erp_integration",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    request_body = OrderExportSettingsRequest,
+    responses(
+        (status = 200, description = "Order export settings", body = OrderExportSettingsResponse),
+    )
+)]
+pub async fn update_order_export_settings(
+    State(pool): State<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(connection_id): Path<Uuid>,
+    Json(request): Json<OrderExportSettingsRequest>,
+) -> Result<impl IntoResponse> {
+    let connection = get_owned_connection(&pool, connection_id, claims.user_id).await?;
+
+    if connection.erp_type == ErpType::FlatFile {
+        return Err(AppError::BadRequest(
+            "Order export is only available for NetSuite and SAP connections".to_string(),
+        ));
+    }
+
+    ErpConnectionService::new(pool.clone())
+        .update_order_export_settings(
+            connection_id,
+            request.export_sales_orders,
+            request.export_purchase_orders,
+            request.order_customer_id.clone(),
+            request.order_vendor_id.clone(),
+        )
+        .await
+        .map_err(|e| match e {
+            crate::services::erp::erp_connection_service::ErpConnectionError::ConfigError(msg) => {
+                AppError::BadRequest(msg)
+            }
+            _ => AppError::Internal(anyhow::anyhow!(e.to_string())),
+        })?;
+
+    let updated = get_owned_connection(&pool, connection_id, claims.user_id).await?;
+
+    // Audit log
+    let audit_service = ComprehensiveAuditService::new(pool);
+    audit_service
+        .log(AuditLogEntry {
+            event_type: "erp_order_export_updated".to_string(),
+            event_category: EventCategory::DataModification,
+            severity: Severity::Info,
+            actor_user_id: Some(claims.user_id),
+            actor_type: "user".to_string(),
+            resource_type: Some("erp_connection".to_string()),
+            resource_id: Some(connection_id.to_string()),
+            resource_name: Some(connection.connection_name.clone()),
+            action: "update".to_string(),
+            action_result: ActionResult::Success,
+            old_values: Some(serde_json::json!(OrderExportSettingsResponse::from(&connection))),
+            new_values: Some(serde_json::json!(OrderExportSettingsResponse::from(&updated))),
+            ..Default::default()
+        })
+        .await
+        .ok();
+
+    Ok(Json(OrderExportSettingsResponse::from(&updated)))
+}
+
+/// ERP orders created for a transaction on the user's connections
+/// GET /api/erp/transactions/:id/orders
+#[utoipa::path(
+    get,
+    path = "/api/erp/transactions/{id}/orders",
+    tag = "erp_integration",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Orders exported for the transaction", body = Vec<crate::services::erp::erp_sync_service::OrderExport>),
+    )
+)]
+pub async fn get_transaction_orders(
+    State(pool): State<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(transaction_id): Path<Uuid>,
+) -> Result<impl IntoResponse> {
+    get_party_transaction_status(&pool, transaction_id, claims.user_id).await?;
+
+    let exports = ErpSyncService::new(pool)
+        .get_order_exports(transaction_id, claims.user_id)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!(e.to_string())))?;
+
+    Ok(Json(exports))
+}
+
+/// Re-run the order export of a completed transaction. Orders already created are kept;
+/// the export IDs are reused as idempotency keys so the ERP never books a duplicate.
+/// POST /api/erp/transactions/:id/orders/retry
+#[utoipa::path(
+    post,
+    path = "/api/erp/transactions/{id}/orders/retry",
+    tag = "erp_integration",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Orders exported for the transaction", body = Vec<crate::services::erp::erp_sync_service::OrderExport>),
+    )
+)]
+pub async fn retry_transaction_orders(
+    State(pool): State<PgPool>,
+    Extension(claims): Extension<Claims>,
+    Path(transaction_id): Path<Uuid>,
+) -> Result<impl IntoResponse> {
+    let status = get_party_transaction_status(&pool, transaction_id, claims.user_id).await?;
+    if status.as_deref() != Some("completed") {
+        return Err(AppError::BadRequest("Only completed transactions are exported to the ERP".to_string()));
+    }
+
+    let sync_service = ErpSyncService::new(pool.clone());
+    sync_service
+        .export_transaction_orders(transaction_id)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!(e.to_string())))?;
+
+    let exports = sync_service
+        .get_order_exports(transaction_id, claims.user_id)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!(e.to_string())))?;
+
+    // Audit log
+    let audit_service = ComprehensiveAuditService::new(pool);
+    audit_service
+        .log(AuditLogEntry {
+            event_type: "erp_order_export_retried".to_string(),
+            event_category: EventCategory::DataModification,
+            severity: Severity::Info,
+            actor_user_id: Some(claims.user_id),
+            actor_type: "user".to_string(),
+            resource_type: Some("transaction".to_string()),
+            resource_id: Some(transaction_id.to_string()),
+            action: "export".to_string(),
+            action_result: ActionResult::Success,
+            event_data: serde_json::json!({
+                "exports": exports.iter().map(|e| serde_json::json!({
+                    "order_type": e.order_type,
+                    "status": e.status,
+                    "erp_document_number": e.erp_document_number,
+                })).collect::<Vec<_>>(),
+            }),
+            ..Default::default()
+        })
+        .await
+        .ok();
+
+    Ok(Json(exports))
+}
+
+/// Status of a transaction the user is the buyer or seller of
+async fn get_party_transaction_status(pool: &PgPool, transaction_id: Uuid, user_id: Uuid) -> Result<Option<String>> {
+    let (seller_id, buyer_id, status) = sqlx::query_as::<_, (Uuid, Uuid, Option<String>)>(
+        "SELECT seller_id, buyer_id, status FROM transactions WHERE id = $1"
+    )
+    .bind(transaction_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Transaction not found".to_string()))?;
+
+    if user_id != seller_id && user_id != buyer_id {
+        return Err(AppError::Forbidden("Access denied".to_string()));
+    }
+
+    Ok(status)
+}
+
+// ============================================================================
+// Webhook Handlers (for real-time ERP updates)
+// ============================================================================
+
+/// NetSuite webhook endpoint (SECURED with HMAC signature verification)
+/// POST /api/erp/webhooks/netsuite/:connection_id
+///
+/// **Security:**
+/// - HMAC-SHA256 signature verification required
+/// - Rate limiting: 100 requests per 15 minutes per connection
+/// - Connection must exist and have webhooks enabled
+/// - All attempts logged to audit table
+/// - IP address tracking
+/// - Payload size limit: 1MB
+///
+/// **Headers Required:**
+/// - X-Webhook-Signature: sha256=<hex_signature>
+/// - Content-Type: application/json
+///
+/// **Rate Limit Headers (Response):**
+/// - X-RateLimit-Limit: requests allowed per window
+/// - X-RateLimit-Remaining: requests remaining in window
+/// - X-RateLimit-Reset: Unix time (seconds) when the window resets
+#[utoipa::path(
+    post,
+    path = "/api/erp/webhooks/netsuite/{id}",
+    tag = "erp_integration",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    request_body(content = serde_json::Value, content_type = "application/json", description = "NetSuite event, signed with the connection's webhook secret"),
+    responses(
+        (status = 200, description = "Event accepted", body = serde_json::Value),
+    )
+)]
+pub async fn netsuite_webhook(
+    State(pool): State<PgPool>,
+    Path(connection_id): Path<Uuid>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<impl IntoResponse> {
+    let start_time = std::time::Instant::now();
+    let request_id = Uuid::new_v4();
+
+    // Extract source IP (if behind proxy, use X-Forwarded-For)
+    let source_ip = headers
+        .get("x-forwarded-for")
+        .and_then(|v| v.to_str().ok())
+        .or_else(|| headers.get("x-real-ip").and_then(|v| v.to_str().ok()))
+        .map(|s| s.to_string());
+
+    // Initialize security service
     let webhook_service = WebhookSecurityService::new(pool.clone())
         .map_err(|e| {
             tracing::error!("Failed to initialize webhook security service: {:?}", e);
@@ -768,7 +2634,11 @@ pub async fn netsuite_webhook(
         };
         let _ = webhook_service.log_webhook_attempt(log).await;
 
-        return Err(AppError::TooManyRequests("Rate limit exceeded for webhook".to_string()));
+        return Ok((
+            rate_limit_result.rate_limit_headers(),
+            AppError::TooManyRequests("Rate limit exceeded for webhook".to_string()),
+        )
+            .into_response());
     }
 
     // Step 3: Verify HMAC signature
@@ -829,17 +2699,60 @@ pub async fn netsuite_webhook(
         request_id
     );
 
-    // Step 5: Process webhook event
-    // TODO: Implement webhook event processing based on event type
-    // - inventory_update: Update inventory quantities
-    // - item_created: Create new pharmaceutical item
-    // - item_updated: Update item details
-    // - order_status: Update order status
-
     // 🔒 SECURITY: Log webhook metadata only, NOT full payload (may contain sensitive data)
     tracing::debug!("NetSuite webhook received for connection: {} (payload size: {} bytes)",
         connection_id, payload.to_string().len());
 
+    // Step 5: Process webhook event
+    // Stock events update mapped inventory and queue unmapped items; other events
+    // (e.g. order status) are acknowledged without processing
+    let field_mapping = ErpFieldMappingService::new(pool.clone())
+        .mapping_for(connection_id, &ErpType::NetSuite)
+        .await?;
+    let event = match parse_netsuite_event(&payload, &field_mapping) {
+        Ok(event) => event,
+        Err(e) => {
+            let log = WebhookAuditLog {
+                connection_id,
+                event_type: "netsuite".to_string(),
+                request_id,
+                source_ip,
+                signature_valid: true,
+                payload_size_bytes: body.len() as i32,
+                http_status: 400,
+                error_message: Some(format!("Invalid event: {}", e)),
+                processing_time_ms: Some(start_time.elapsed().as_millis() as i32),
+            };
+            let _ = webhook_service.log_webhook_attempt(log).await;
+            return Err(AppError::BadRequest(format!("Invalid webhook event: {}", e)));
+        }
+    };
+
+    let sync_result = if event.is_stock_event() {
+        match ErpSyncService::new(pool.clone()).process_webhook_event(connection_id, &event).await {
+            Ok(result) => Some(result),
+            Err(e) => {
+                tracing::error!("Failed to process NetSuite {} event for connection {}: {}", event.event_type, connection_id, e);
+                let log = WebhookAuditLog {
+                    connection_id,
+                    event_type: "netsuite".to_string(),
+                    request_id,
+                    source_ip,
+                    signature_valid: true,
+                    payload_size_bytes: body.len() as i32,
+                    http_status: 500,
+                    error_message: Some(e.to_string()),
+                    processing_time_ms: Some(start_time.elapsed().as_millis() as i32),
+                };
+                let _ = webhook_service.log_webhook_attempt(log).await;
+                return Err(AppError::Internal(anyhow::anyhow!("Webhook processing failed")));
+            }
+        }
+    } else {
+        tracing::info!("Acknowledged unhandled NetSuite event '{}' for connection {}", event.event_type, connection_id);
+        None
+    };
+
     // Step 6: Log successful webhook processing
     let processing_time = start_time.elapsed().as_millis() as i32;
     let log = WebhookAuditLog {
@@ -858,16 +2771,18 @@ pub async fn netsuite_webhook(
     // Return response with rate limit headers
     Ok((
         StatusCode::OK,
-        [
-            ("X-Request-ID", request_id.to_string()),
-            ("X-RateLimit-Remaining", rate_limit_result.requests_remaining.to_string()),
-        ],
+        [("X-Request-ID", request_id.to_string())],
+        rate_limit_result.rate_limit_headers(),
         Json(serde_json::json!({
             "status": "accepted",
             "request_id": request_id,
+            "event_type": event.event_type,
+            "processed": sync_result.is_some(),
+            "result": sync_result,
             "processing_time_ms": processing_time
         }))
-    ))
+    )
+        .into_response())
 }
 
 /// SAP webhook endpoint (SECURED with HMAC signature verification)
@@ -886,8 +2801,21 @@ pub async fn netsuite_webhook(
 /// - Content-Type: application/json
 ///
 /// **Rate Limit Headers (Response):**
+/// - X-RateLimit-Limit: requests allowed per window
 /// - X-RateLimit-Remaining: requests remaining in window
-/// - X-RateLimit-Reset: timestamp when window resets
+/// - X-RateLimit-Reset: Unix time (seconds) when the window resets
+#[utoipa::path(
+    post,
+    path = "/api/erp/webhooks/sap/{id}",
+    tag = "erp_integration",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    request_body(content = serde_json::Value, content_type = "application/json", description = "SAP event, signed with the connection's webhook secret"),
+    responses(
+        (status = 200, description = "Event accepted", body = serde_json::Value),
+    )
+)]
 pub async fn sap_webhook(
     State(pool): State<PgPool>,
     Path(connection_id): Path<Uuid>,
@@ -968,7 +2896,11 @@ pub async fn sap_webhook(
         };
         let _ = webhook_service.log_webhook_attempt(log).await;
 
-        return Err(AppError::TooManyRequests("Rate limit exceeded for webhook".to_string()));
+        return Ok((
+            rate_limit_result.rate_limit_headers(),
+            AppError::TooManyRequests("Rate limit exceeded for webhook".to_string()),
+        )
+            .into_response());
     }
 
     // Step 3: Verify HMAC signature
@@ -1029,16 +2961,60 @@ pub async fn sap_webhook(
         request_id
     );
 
-    // Step 5: Process webhook event
-    // TODO: Implement webhook event processing based on event type
-    // - material_changed: Update inventory quantities
-    // - material_created: Create new pharmaceutical item
-    // - purchase_order_status: Update order status
-
     // 🔒 SECURITY: Log webhook metadata only, NOT full payload (may contain sensitive data)
     tracing::debug!("SAP webhook received for connection: {} (payload size: {} bytes)",
         connection_id, payload.to_string().len());
 
+    // Step 5: Process webhook event
+    // Stock events update mapped inventory and queue unmapped items; other events
+    // (e.g. order status) are acknowledged without processing
+    let field_mapping = ErpFieldMappingService::new(pool.clone())
+        .mapping_for(connection_id, &ErpType::SapS4Hana)
+        .await?;
+    let event = match parse_sap_event(&payload, &field_mapping) {
+        Ok(event) => event,
+        Err(e) => {
+            let log = WebhookAuditLog {
+                connection_id,
+                event_type: "sap".to_string(),
+                request_id,
+                source_ip,
+                signature_valid: true,
+                payload_size_bytes: body.len() as i32,
+                http_status: 400,
+                error_message: Some(format!("Invalid event: {}", e)),
+                processing_time_ms: Some(start_time.elapsed().as_millis() as i32),
+            };
+            let _ = webhook_service.log_webhook_attempt(log).await;
+            return Err(AppError::BadRequest(format!("Invalid webhook event: {}", e)));
+        }
+    };
+
+    let sync_result = if event.is_stock_event() {
+        match ErpSyncService::new(pool.clone()).process_webhook_event(connection_id, &event).await {
+            Ok(result) => Some(result),
+            Err(e) => {
+                tracing::error!("Failed to process SAP {} event for connection {}: {}", event.event_type, connection_id, e);
+                let log = WebhookAuditLog {
+                    connection_id,
+                    event_type: "sap".to_string(),
+                    request_id,
+                    source_ip,
+                    signature_valid: true,
+                    payload_size_bytes: body.len() as i32,
+                    http_status: 500,
+                    error_message: Some(e.to_string()),
+                    processing_time_ms: Some(start_time.elapsed().as_millis() as i32),
+                };
+                let _ = webhook_service.log_webhook_attempt(log).await;
+                return Err(AppError::Internal(anyhow::anyhow!("Webhook processing failed")));
+            }
+        }
+    } else {
+        tracing::info!("Acknowledged unhandled SAP event '{}' for connection {}", event.event_type, connection_id);
+        None
+    };
+
     // Step 6: Log successful webhook processing
     let processing_time = start_time.elapsed().as_millis() as i32;
     let log = WebhookAuditLog {
@@ -1057,14 +3033,16 @@ pub async fn sap_webhook(
     // Return response with rate limit headers
     Ok((
         StatusCode::OK,
-        [
-            ("X-Request-ID", request_id.to_string()),
-            ("X-RateLimit-Remaining", rate_limit_result.requests_remaining.to_string()),
-        ],
+        [("X-Request-ID", request_id.to_string())],
+        rate_limit_result.rate_limit_headers(),
         Json(serde_json::json!({
             "status": "accepted",
             "request_id": request_id,
+            "event_type": event.event_type,
+            "processed": sync_result.is_some(),
+            "result": sync_result,
             "processing_time_ms": processing_time
         }))
-    ))
+    )
+        .into_response())
 }
@@ -0,0 +1,359 @@
+/// FHIR Facade Handlers
+///
+/// Catalog and inventory data as FHIR resources for hospital systems that only integrate
+/// over FHIR: Medication (our catalog), MedicationKnowledge (the OpenFDA catalog) and
+/// InventoryItem (marketplace listings), read and searched by NDC or ATC code. Clients
+/// discover the token endpoint through the capability statement or the SMART
+/// configuration, exchange the credentials of a client registered at /api/fhir/clients
+/// for a bearer token, and get `application/fhir+json` responses; errors are
+/// OperationOutcomes.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Form, Json,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use serde_json::Value;
+use uuid::Uuid;
+use crate::{
+    config::AppConfig,
+    middleware::{error_handling::{AppError, Result}, Claims, JwtService},
+    models::fhir::*,
+    services::{fhir_base_url, FhirPage, FhirService},
+};
+
+/// When this instance started serving the capability statement
+static PUBLISHED_AT: Lazy<chrono::DateTime<Utc>> = Lazy::new(Utc::now);
+
+fn service(config: &AppConfig) -> FhirService {
+    FhirService::new(config.database_pool.clone(), &config.encryption_key)
+}
+
+/// A FHIR resource, served as `application/fhir+json`
+pub struct FhirJson(pub Value);
+
+impl IntoResponse for FhirJson {
+    fn into_response(self) -> Response {
+        ([(header::CONTENT_TYPE, FHIR_JSON)], Json(self.0)).into_response()
+    }
+}
+
+/// An `AppError` as an OperationOutcome with the client-safe message
+pub struct FhirError(AppError);
+
+impl From<AppError> for FhirError {
+    fn from(err: AppError) -> Self {
+        Self(err)
+    }
+}
+
+impl IntoResponse for FhirError {
+    fn into_response(self) -> Response {
+        crate::middleware::error_tracking::capture_app_error(&self.0);
+        let (status, message) = self.0.client_error();
+        (status, FhirJson(operation_outcome(status.as_u16(), &message))).into_response()
+    }
+}
+
+type FhirResult = std::result::Result<FhirJson, FhirError>;
+
+/// Searchset Bundle with `self` and `next` links carrying the search parameters
+fn search_response(resource_type: &str, params: &FhirSearchParams, page: FhirPage) -> FhirJson {
+    let base_url = fhir_base_url();
+    let (limit, offset) = params.page();
+    let link = |offset: i64| {
+        let mut url = format!("{}/{}?_count={}&_offset={}", base_url, resource_type, limit, offset);
+        if let Some(code) = params.code.as_deref() {
+            url.push_str(&format!("&code={}", utf8_percent_encode(code, NON_ALPHANUMERIC)));
+        }
+        url
+    };
+
+    let next_url = page.has_more.then(|| link(offset + limit));
+    FhirJson(search_bundle(&base_url, page.resources, link(offset), next_url))
+}
+
+// ============================================================================
+// DISCOVERY
+// ============================================================================
+
+/// GET /fhir/metadata
+#[utoipa::path(
+    get,
+    path = "/fhir/metadata",
+    tag = "fhir",
+    operation_id = "fhir_capability_statement",
+    summary = "CapabilityStatement: resources, search parameters and the SMART token endpoint",
+    responses(
+        (status = 200, description = "CapabilityStatement", body = serde_json::Value, content_type = "application/fhir+json"),
+    )
+)]
+pub async fn capability_statement() -> FhirJson {
+    FhirJson(crate::models::fhir::capability_statement(&fhir_base_url(), env!("CARGO_PKG_VERSION"), *PUBLISHED_AT))
+}
+
+/// GET /fhir/.well-known/smart-configuration
+#[utoipa::path(
+    get,
+    path = "/fhir/.well-known/smart-configuration",
+    tag = "fhir",
+    operation_id = "fhir_smart_configuration",
+    summary = "SMART configuration: token endpoint, grant types and scopes",
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
+pub async fn smart_configuration() -> Json<Value> {
+    Json(crate::models::fhir::smart_configuration(&fhir_base_url()))
+}
+
+// ============================================================================
+// TOKEN
+// ============================================================================
+
+fn token_error(status: StatusCode, error: &str, description: &str) -> Response {
+    let body = SmartTokenError { error: error.to_string(), error_description: description.to_string() };
+    (status, [(header::CACHE_CONTROL, "no-store")], Json(body)).into_response()
+}
+
+/// Client id and secret of an HTTP Basic `Authorization` header
+fn basic_credentials(headers: &HeaderMap) -> Option<(String, String)> {
+    let encoded = headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Basic ")?;
+    let decoded = String::from_utf8(STANDARD.decode(encoded.trim()).ok()?).ok()?;
+    let (client_id, client_secret) = decoded.split_once(':')?;
+    Some((client_id.to_string(), client_secret.to_string()))
+}
+
+/// POST /fhir/auth/token
+/// SMART backend services style token exchange (client_credentials grant)
+#[utoipa::path(
+    post,
+    path = "/fhir/auth/token",
+    tag = "fhir",
+    operation_id = "fhir_token",
+    summary = "Exchange FHIR client credentials (HTTP Basic or form fields) for a bearer token",
+    request_body(content = SmartTokenRequest, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Success", body = SmartTokenResponse),
+        (status = 400, description = "unsupported_grant_type or invalid_scope", body = SmartTokenError),
+        (status = 401, description = "invalid_client", body = SmartTokenError),
+    )
+)]
+pub async fn token(
+    State(config): State<AppConfig>,
+    headers: HeaderMap,
+    Form(request): Form<SmartTokenRequest>,
+) -> Response {
+    if request.grant_type != "client_credentials" {
+        return token_error(StatusCode::BAD_REQUEST, "unsupported_grant_type", "Only client_credentials is supported");
+    }
+
+    let credentials = basic_credentials(&headers)
+        .or_else(|| request.client_id.clone().zip(request.client_secret.clone()));
+    let Some((client_id, client_secret)) = credentials else {
+        return token_error(StatusCode::UNAUTHORIZED, "invalid_client", "Client credentials required");
+    };
+
+    let Some(scope) = granted_scopes(request.scope.as_deref()) else {
+        return token_error(StatusCode::BAD_REQUEST, "invalid_scope", "None of the requested scopes is supported");
+    };
+
+    let jwt_service = JwtService::new(&config.jwt_secret);
+    match service(&config).issue_token(&jwt_service, &client_id, &client_secret, scope).await {
+        Ok(token) => {
+            tracing::info!("Audit: FHIR client {} got a token", crate::utils::log_sanitizer::sanitize_for_log(&client_id));
+            ([(header::CACHE_CONTROL, "no-store")], Json(token)).into_response()
+        }
+        Err(err) => {
+            crate::middleware::error_tracking::capture_app_error(&err);
+            let (status, message) = err.client_error();
+            let error = match status {
+                StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => "invalid_client",
+                status if status.is_server_error() => "server_error",
+                _ => "invalid_request",
+            };
+            token_error(status, error, &message)
+        }
+    }
+}
+
+// ============================================================================
+// RESOURCES
+// ============================================================================
+
+/// GET /fhir/Medication
+#[utoipa::path(
+    get,
+    path = "/fhir/Medication",
+    tag = "fhir",
+    operation_id = "fhir_search_medication",
+    summary = "Search catalog products as Medication resources",
+    params(FhirSearchParams),
+    responses(
+        (status = 200, description = "Searchset Bundle", body = serde_json::Value, content_type = "application/fhir+json"),
+    )
+)]
+pub async fn search_medication(State(config): State<AppConfig>, Query(params): Query<FhirSearchParams>) -> FhirResult {
+    let page = service(&config).search_medications(&params).await?;
+    Ok(search_response("Medication", &params, page))
+}
+
+/// GET /fhir/Medication/:id
+#[utoipa::path(
+    get,
+    path = "/fhir/Medication/{id}",
+    tag = "fhir",
+    operation_id = "fhir_read_medication",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Medication", body = serde_json::Value, content_type = "application/fhir+json"),
+    )
+)]
+pub async fn read_medication(State(config): State<AppConfig>, Path(id): Path<String>) -> FhirResult {
+    let id = Uuid::parse_str(&id).map_err(|_| AppError::NotFound("Medication not found".to_string()))?;
+    Ok(FhirJson(service(&config).read_medication(id).await?))
+}
+
+/// GET /fhir/MedicationKnowledge
+#[utoipa::path(
+    get,
+    path = "/fhir/MedicationKnowledge",
+    tag = "fhir",
+    operation_id = "fhir_search_medication_knowledge",
+    summary = "Search the OpenFDA catalog as MedicationKnowledge resources, by NDC or ATC prefix",
+    params(FhirSearchParams),
+    responses(
+        (status = 200, description = "Searchset Bundle", body = serde_json::Value, content_type = "application/fhir+json"),
+    )
+)]
+pub async fn search_medication_knowledge(
+    State(config): State<AppConfig>,
+    Query(params): Query<FhirSearchParams>,
+) -> FhirResult {
+    let page = service(&config).search_medication_knowledge(&params).await?;
+    Ok(search_response("MedicationKnowledge", &params, page))
+}
+
+/// GET /fhir/MedicationKnowledge/:id
+#[utoipa::path(
+    get,
+    path = "/fhir/MedicationKnowledge/{id}",
+    tag = "fhir",
+    operation_id = "fhir_read_medication_knowledge",
+    params(
+        ("id" = String, Path, description = "Product NDC, e.g. 0002-1433"),
+    ),
+    responses(
+        (status = 200, description = "MedicationKnowledge", body = serde_json::Value, content_type = "application/fhir+json"),
+    )
+)]
+pub async fn read_medication_knowledge(State(config): State<AppConfig>, Path(id): Path<String>) -> FhirResult {
+    Ok(FhirJson(service(&config).read_medication_knowledge(&id).await?))
+}
+
+/// GET /fhir/InventoryItem
+#[utoipa::path(
+    get,
+    path = "/fhir/InventoryItem",
+    tag = "fhir",
+    operation_id = "fhir_search_inventory_item",
+    summary = "Search marketplace listings as InventoryItem resources",
+    params(FhirSearchParams),
+    responses(
+        (status = 200, description = "Searchset Bundle", body = serde_json::Value, content_type = "application/fhir+json"),
+    )
+)]
+pub async fn search_inventory_item(State(config): State<AppConfig>, Query(params): Query<FhirSearchParams>) -> FhirResult {
+    let page = service(&config).search_inventory_items(&params).await?;
+    Ok(search_response("InventoryItem", &params, page))
+}
+
+/// GET /fhir/InventoryItem/:id
+#[utoipa::path(
+    get,
+    path = "/fhir/InventoryItem/{id}",
+    tag = "fhir",
+    operation_id = "fhir_read_inventory_item",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "InventoryItem", body = serde_json::Value, content_type = "application/fhir+json"),
+    )
+)]
+pub async fn read_inventory_item(State(config): State<AppConfig>, Path(id): Path<String>) -> FhirResult {
+    let id = Uuid::parse_str(&id).map_err(|_| AppError::NotFound("InventoryItem not found".to_string()))?;
+    Ok(FhirJson(service(&config).read_inventory_item(id).await?))
+}
+
+// ============================================================================
+// CLIENTS
+// ============================================================================
+
+/// GET /api/fhir/clients
+#[utoipa::path(
+    get,
+    path = "/api/fhir/clients",
+    tag = "fhir",
+    responses(
+        (status = 200, description = "Success", body = Vec<FhirClient>),
+    )
+)]
+pub async fn list_fhir_clients(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Vec<FhirClient>>> {
+    Ok(Json(service(&config).list_clients(claims.user_id).await?))
+}
+
+/// POST /api/fhir/clients
+#[utoipa::path(
+    post,
+    path = "/api/fhir/clients",
+    tag = "fhir",
+    summary = "Register a FHIR client; the secret is only returned here",
+    request_body = CreateFhirClientRequest,
+    responses(
+        (status = 201, description = "Created", body = FhirClientWithSecret),
+    )
+)]
+pub async fn create_fhir_client(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<CreateFhirClientRequest>,
+) -> Result<(StatusCode, Json<FhirClientWithSecret>)> {
+    let client = service(&config).create_client(claims.user_id, request).await?;
+    Ok((StatusCode::CREATED, Json(client)))
+}
+
+/// DELETE /api/fhir/clients/:id
+#[utoipa::path(
+    delete,
+    path = "/api/fhir/clients/{id}",
+    tag = "fhir",
+    summary = "Revoke a FHIR client; tokens it already has expire within minutes",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = FhirClient),
+    )
+)]
+pub async fn revoke_fhir_client(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<FhirClient>> {
+    Ok(Json(service(&config).revoke_client(id, claims.user_id).await?))
+}
@@ -0,0 +1,35 @@
+/// GraphQL Handler
+///
+/// Runs a query against the schema in `crate::graphql` as the authenticated caller.
+/// Each request gets its own DataLoaders, so batched lookups are never shared between
+/// callers.
+
+use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
+use axum::{extract::State, Extension};
+use crate::{
+    config::AppConfig,
+    graphql::{self, loaders::with_loaders},
+    middleware::{error_handling::Result, Claims},
+};
+
+/// POST /graphql
+/// Execute a GraphQL query or mutation; Apollo persisted queries send
+/// `extensions.persistedQuery.sha256Hash` instead of the query text
+#[utoipa::path(
+    post,
+    path = "/graphql",
+    tag = "graphql",
+    summary = "Execute a GraphQL query or mutation",
+    request_body = serde_json::Value,
+    responses(
+        (status = 200, description = "GraphQL response; field errors are reported in `errors`", body = serde_json::Value),
+    )
+)]
+pub async fn graphql(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    request: GraphQLRequest,
+) -> Result<GraphQLResponse> {
+    let request = with_loaders(request.into_inner().data(claims).data(config.clone()), &config)?;
+    Ok(graphql::schema().execute(request).await.into())
+}
@@ -15,6 +15,20 @@ use crate::{
 
 /// POST /api/inquiry-assistant/inquiries/:inquiry_id/suggestions
 /// Generate AI suggestion for inquiry response
+#[utoipa::path(
+    post,
+    path = "/api/inquiry-assistant/inquiries/{inquiry_id}/suggestions",
+    tag = "inquiry_assistant",
+    operation_id = "inquiry_assistant_generate_suggestion",
+    summary = "Generate AI suggestion for inquiry response",
+    params(
+        ("inquiry_id" = Uuid, Path),
+    ),
+    request_body = GenerateSuggestionRequest,
+    responses(
+        (status = 200, description = "Success", body = SuggestionResponse),
+    )
+)]
 pub async fn generate_suggestion(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -42,6 +56,8 @@ pub async fn generate_suggestion(
         claims.user_id,
         request.suggestion_type,
         request.custom_instructions,
+        request.language,
+        request.use_memory.unwrap_or(true),
     ).await?;
 
     Ok(Json(suggestion.into()))
@@ -49,6 +65,19 @@ pub async fn generate_suggestion(
 
 /// POST /api/inquiry-assistant/suggestions/:suggestion_id/accept
 /// Accept a suggestion and send it as a message
+#[utoipa::path(
+    post,
+    path = "/api/inquiry-assistant/suggestions/{suggestion_id}/accept",
+    tag = "inquiry_assistant",
+    summary = "Accept a suggestion and send it as a message",
+    params(
+        ("suggestion_id" = Uuid, Path),
+    ),
+    request_body = AcceptSuggestionRequest,
+    responses(
+        (status = 200, description = "Success", body = AcceptSuggestionResponse),
+    )
+)]
 pub async fn accept_suggestion(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -82,8 +111,117 @@ pub async fn accept_suggestion(
     }))
 }
 
+/// POST /api/inquiry-assistant/suggestions/:suggestion_id/reject
+/// Reject a suggestion so later suggestions for the inquiry avoid repeating it
+#[utoipa::path(
+    post,
+    path = "/api/inquiry-assistant/suggestions/{suggestion_id}/reject",
+    tag = "inquiry_assistant",
+    summary = "Reject a suggestion so later suggestions for the inquiry avoid repeating it",
+    params(
+        ("suggestion_id" = Uuid, Path),
+    ),
+    request_body = Option<RejectSuggestionRequest>,
+    responses(
+        (status = 200, description = "Success", body = AssistantTurn),
+    )
+)]
+pub async fn reject_suggestion(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(suggestion_id): Path<Uuid>,
+    request: Option<Json<RejectSuggestionRequest>>,
+) -> Result<Json<AssistantTurn>> {
+    let claude_api_key = std::env::var("ANTHROPIC_API_KEY")
+        .map_err(|_| crate::middleware::error_handling::AppError::Internal(
+            anyhow::anyhow!("ANTHROPIC_API_KEY not configured")
+        ))?;
+
+    let request = request.map(|Json(r)| r).unwrap_or_default();
+    let service = InquiryAssistantService::new(config.database_pool.clone(), claude_api_key);
+    let turn = service.reject_suggestion(suggestion_id, claims.user_id, request.reason).await?;
+
+    Ok(Json(turn))
+}
+
+/// GET /api/inquiry-assistant/inquiries/:inquiry_id/thread
+/// Assistant conversation memory for an inquiry
+#[utoipa::path(
+    get,
+    path = "/api/inquiry-assistant/inquiries/{inquiry_id}/thread",
+    tag = "inquiry_assistant",
+    summary = "Assistant conversation memory for an inquiry",
+    params(
+        ("inquiry_id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = AssistantThreadResponse),
+    )
+)]
+pub async fn get_assistant_thread(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(inquiry_id): Path<Uuid>,
+) -> Result<Json<AssistantThreadResponse>> {
+    let claude_api_key = std::env::var("ANTHROPIC_API_KEY")
+        .map_err(|_| crate::middleware::error_handling::AppError::Internal(
+            anyhow::anyhow!("ANTHROPIC_API_KEY not configured")
+        ))?;
+
+    let service = InquiryAssistantService::new(config.database_pool.clone(), claude_api_key);
+    let thread = service.get_thread(inquiry_id, claims.user_id).await?;
+
+    Ok(Json(thread))
+}
+
+/// DELETE /api/inquiry-assistant/inquiries/:inquiry_id/thread
+/// Reset the assistant's memory for an inquiry
+#[utoipa::path(
+    delete,
+    path = "/api/inquiry-assistant/inquiries/{inquiry_id}/thread",
+    tag = "inquiry_assistant",
+    summary = "Reset the assistant's memory for an inquiry",
+    params(
+        ("inquiry_id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
+pub async fn clear_assistant_thread(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(inquiry_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>> {
+    let claude_api_key = std::env::var("ANTHROPIC_API_KEY")
+        .map_err(|_| crate::middleware::error_handling::AppError::Internal(
+            anyhow::anyhow!("ANTHROPIC_API_KEY not configured")
+        ))?;
+
+    let service = InquiryAssistantService::new(config.database_pool.clone(), claude_api_key);
+    let turns_removed = service.clear_thread(inquiry_id, claims.user_id).await?;
+
+    Ok(Json(serde_json::json!({
+        "inquiry_id": inquiry_id,
+        "turns_removed": turns_removed,
+    })))
+}
+
 /// GET /api/inquiry-assistant/suggestions/:suggestion_id
 /// Get suggestion by ID
+#[utoipa::path(
+    get,
+    path = "/api/inquiry-assistant/suggestions/{suggestion_id}",
+    tag = "inquiry_assistant",
+    operation_id = "inquiry_assistant_get_suggestion",
+    summary = "Get suggestion by ID",
+    params(
+        ("suggestion_id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = SuggestionResponse),
+    )
+)]
 pub async fn get_suggestion(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -102,6 +240,18 @@ pub async fn get_suggestion(
 
 /// GET /api/inquiry-assistant/inquiries/:inquiry_id/suggestions
 /// Get all suggestions for an inquiry
+#[utoipa::path(
+    get,
+    path = "/api/inquiry-assistant/inquiries/{inquiry_id}/suggestions",
+    tag = "inquiry_assistant",
+    summary = "Get all suggestions for an inquiry",
+    params(
+        ("inquiry_id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<SuggestionResponse>),
+    )
+)]
 pub async fn get_inquiry_suggestions(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -122,8 +272,72 @@ pub async fn get_inquiry_suggestions(
     Ok(Json(responses))
 }
 
+/// GET /api/inquiry-assistant/preferences
+/// Get user's language preferences for suggestions
+#[utoipa::path(
+    get,
+    path = "/api/inquiry-assistant/preferences",
+    tag = "inquiry_assistant",
+    summary = "Get user's language preferences for suggestions",
+    responses(
+        (status = 200, description = "Success", body = LanguagePreferences),
+    )
+)]
+pub async fn get_language_preferences(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<LanguagePreferences>> {
+    let claude_api_key = std::env::var("ANTHROPIC_API_KEY")
+        .map_err(|_| crate::middleware::error_handling::AppError::Internal(
+            anyhow::anyhow!("ANTHROPIC_API_KEY not configured")
+        ))?;
+
+    let service = InquiryAssistantService::new(config.database_pool.clone(), claude_api_key);
+    let preferences = service.get_language_preferences(claims.user_id).await?;
+
+    Ok(Json(preferences))
+}
+
+/// PUT /api/inquiry-assistant/preferences
+/// Set preferred and default negotiation languages
+#[utoipa::path(
+    put,
+    path = "/api/inquiry-assistant/preferences",
+    tag = "inquiry_assistant",
+    summary = "Set preferred and default negotiation languages",
+    request_body = UpdateLanguagePreferencesRequest,
+    responses(
+        (status = 200, description = "Success", body = LanguagePreferences),
+    )
+)]
+pub async fn update_language_preferences(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<UpdateLanguagePreferencesRequest>,
+) -> Result<Json<LanguagePreferences>> {
+    let claude_api_key = std::env::var("ANTHROPIC_API_KEY")
+        .map_err(|_| crate::middleware::error_handling::AppError::Internal(
+            anyhow::anyhow!("ANTHROPIC_API_KEY not configured")
+        ))?;
+
+    let service = InquiryAssistantService::new(config.database_pool.clone(), claude_api_key);
+    let preferences = service.update_language_preferences(claims.user_id, &request).await?;
+
+    Ok(Json(preferences))
+}
+
 /// GET /api/inquiry-assistant/quota
 /// Get user's inquiry assistant quota status
+#[utoipa::path(
+    get,
+    path = "/api/inquiry-assistant/quota",
+    tag = "inquiry_assistant",
+    operation_id = "inquiry_assistant_get_quota",
+    summary = "Get user's inquiry assistant quota status",
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
 pub async fn get_quota(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -135,10 +349,14 @@ pub async fn get_quota(
 
     let service = InquiryAssistantService::new(config.database_pool.clone(), claude_api_key);
     let (limit, used, remaining) = service.get_quota_status(claims.user_id).await?;
+    let usage = crate::services::AiQuotaService::new(config.database_pool.clone())
+        .get_usage(claims.user_id)
+        .await?;
 
     Ok(Json(serde_json::json!({
         "assist_limit": limit,
         "assists_used": used,
-        "assists_remaining": remaining
+        "assists_remaining": remaining,
+        "usage": usage
     })))
 }
@@ -12,6 +12,15 @@ use crate::{
 };
 
 /// Create a new message in an inquiry conversation
+#[utoipa::path(
+    post,
+    path = "/api/marketplace/inquiries/{id}/messages",
+    tag = "inquiry_messages",
+    request_body = CreateInquiryMessageRequest,
+    responses(
+        (status = 200, description = "Success", body = InquiryMessageResponse),
+    )
+)]
 pub async fn create_message(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -74,6 +83,17 @@ pub async fn create_message(
 }
 
 /// Get all messages for an inquiry
+#[utoipa::path(
+    get,
+    path = "/api/marketplace/inquiries/{id}/messages",
+    tag = "inquiry_messages",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<InquiryMessageResponse>),
+    )
+)]
 pub async fn get_inquiry_messages(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -119,6 +139,17 @@ pub async fn get_inquiry_messages(
 }
 
 /// Get message count for an inquiry
+#[utoipa::path(
+    get,
+    path = "/api/marketplace/inquiries/{id}/messages/count",
+    tag = "inquiry_messages",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
 pub async fn get_message_count(
     State(config): State<AppConfig>,
     Path(inquiry_id): Path<Uuid>,
@@ -8,11 +8,23 @@ use crate::{
     models::{
         inventory::{CreateInventoryRequest, UpdateInventoryRequest, SearchInventoryRequest},
     },
-    services::InventoryService,
-    middleware::{error_handling::Result, Claims},
-    config::AppConfig,
+    services::{expand_catalog_entries, InventoryService, ListingRightsService},
+    middleware::{error_handling::Result, Claims, ExpandQuery},
+    config::{AppConfig, ReadConsistency},
 };
 
+/// `expand=` of an inventory item's product with its OpenFDA catalog entry
+const EXPAND_CATALOG_ENTRY: &str = "pharmaceutical.catalog_entry";
+
+#[utoipa::path(
+    post,
+    path = "/api/inventory",
+    tag = "inventory",
+    request_body = CreateInventoryRequest,
+    responses(
+        (status = 200, description = "Success", body = crate::models::inventory::InventoryResponse),
+    )
+)]
 pub async fn add_inventory(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -21,6 +33,9 @@ pub async fn add_inventory(
     request.validate()
         .map_err(|e| crate::middleware::error_handling::AppError::Validation(e))?;
 
+    // Companies with expired licenses can't list until a renewal is verified
+    ListingRightsService::new(config.database_pool.clone()).ensure_can_list(claims.user_id).await?;
+
     let inventory_service = InventoryService::new(
         crate::repositories::InventoryRepository::new(config.database_pool.clone()),
         crate::repositories::PharmaceuticalRepository::new(config.database_pool.clone()),
@@ -30,25 +45,59 @@ pub async fn add_inventory(
     Ok(Json(inventory))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/inventory/{id}",
+    tag = "inventory",
+    params(
+        ("id" = uuid::Uuid, Path),
+        ExpandQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = crate::models::inventory::InventoryResponse),
+    )
+)]
 pub async fn get_inventory(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
     Path(inventory_id): Path<uuid::Uuid>,
+    Query(expand): Query<ExpandQuery>,
 ) -> Result<Json<crate::models::inventory::InventoryResponse>> {
+    let expansions = expand.expansions(&[EXPAND_CATALOG_ENTRY])?;
+
     let inventory_service = InventoryService::new(
         crate::repositories::InventoryRepository::new(config.database_pool.clone()),
         crate::repositories::PharmaceuticalRepository::new(config.database_pool.clone()),
     );
 
-    let inventory = inventory_service.get_inventory(inventory_id, claims.user_id).await?;
+    let mut inventory = inventory_service.get_inventory(inventory_id, claims.user_id).await?;
+    if expansions.contains(EXPAND_CATALOG_ENTRY) {
+        let catalog = crate::repositories::OpenFdaRepository::new(config.database_pool.clone());
+        expand_catalog_entries(&catalog, vec![&mut inventory.pharmaceutical]).await?;
+    }
     Ok(Json(inventory))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/inventory/my",
+    tag = "inventory",
+    params(
+        ("limit" = Option<i64>, Query),
+        ("offset" = Option<i64>, Query),
+        ExpandQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<crate::models::inventory::InventoryResponse>),
+    )
+)]
 pub async fn get_user_inventory(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
     Query(params): Query<serde_json::Value>,
+    Query(expand): Query<ExpandQuery>,
 ) -> Result<Json<Vec<crate::models::inventory::InventoryResponse>>> {
+    let expansions = expand.expansions(&[EXPAND_CATALOG_ENTRY])?;
     let limit = params.get("limit").and_then(|v| v.as_i64()).map(|v| v as i64);
     let offset = params.get("offset").and_then(|v| v.as_i64()).map(|v| v as i64);
 
@@ -57,10 +106,26 @@ pub async fn get_user_inventory(
         crate::repositories::PharmaceuticalRepository::new(config.database_pool.clone()),
     );
 
-    let inventories = inventory_service.get_user_inventory(claims.user_id, limit, offset).await?;
+    let mut inventories = inventory_service.get_user_inventory(claims.user_id, limit, offset).await?;
+    if expansions.contains(EXPAND_CATALOG_ENTRY) {
+        let catalog = crate::repositories::OpenFdaRepository::new(config.database_pool.clone());
+        expand_catalog_entries(&catalog, inventories.iter_mut().map(|item| &mut item.pharmaceutical).collect()).await?;
+    }
     Ok(Json(inventories))
 }
 
+#[utoipa::path(
+    put,
+    path = "/api/inventory/{id}",
+    tag = "inventory",
+    params(
+        ("id" = uuid::Uuid, Path),
+    ),
+    request_body = UpdateInventoryRequest,
+    responses(
+        (status = 200, description = "Success", body = crate::models::inventory::InventoryResponse),
+    )
+)]
 pub async fn update_inventory(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -70,6 +135,9 @@ pub async fn update_inventory(
     request.validate()
         .map_err(|e| crate::middleware::error_handling::AppError::Validation(e))?;
 
+    // Companies with expired licenses can't list until a renewal is verified
+    ListingRightsService::new(config.database_pool.clone()).ensure_can_list(claims.user_id).await?;
+
     let inventory_service = InventoryService::new(
         crate::repositories::InventoryRepository::new(config.database_pool.clone()),
         crate::repositories::PharmaceuticalRepository::new(config.database_pool.clone()),
@@ -79,6 +147,17 @@ pub async fn update_inventory(
     Ok(Json(inventory))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/api/inventory/{id}",
+    tag = "inventory",
+    params(
+        ("id" = uuid::Uuid, Path),
+    ),
+    responses(
+        (status = 204, description = "No content"),
+    )
+)]
 pub async fn delete_inventory(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -107,16 +186,38 @@ pub async fn delete_inventory(
 /// - Standard API rate limits apply
 /// - Audited with user ID tracking
 ///
+/// **Search engine:** `q` and name searches are typo-tolerant when a search engine is
+/// configured; `facets` (e.g. `manufacturer,dosage_form`) wraps the results with
+/// facet counts. Postgres answers when the engine is unavailable.
+///
+#[utoipa::path(
+    get,
+    path = "/api/marketplace/search",
+    tag = "inventory",
+    params(
+        SearchInventoryRequest,
+    ),
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
 pub async fn search_marketplace(
     State(config): State<AppConfig>,
     claims: Option<Extension<Claims>>,  // 🔒 SECURITY: Optional auth - Extract if present
     axum::extract::ConnectInfo(addr): axum::extract::ConnectInfo<std::net::SocketAddr>,
     Query(mut request): Query<SearchInventoryRequest>,
-) -> Result<Json<Vec<crate::models::inventory::InventoryResponse>>> {
+) -> Result<Json<serde_json::Value>> {
+    // Sellers who just changed a listing search the primary until the replica has it
+    let read_pool = config.read_pool(match &claims {
+        Some(claims) => ReadConsistency::ReadYourWrites(claims.user_id),
+        None => ReadConsistency::Relaxed,
+    });
     let inventory_service = InventoryService::new(
-        crate::repositories::InventoryRepository::new(config.database_pool.clone()),
-        crate::repositories::PharmaceuticalRepository::new(config.database_pool.clone()),
+        crate::repositories::InventoryRepository::new(read_pool.clone()),
+        crate::repositories::PharmaceuticalRepository::new(read_pool),
     );
+    // `facets` wraps the results with facet counts
+    let with_facets = request.facets.is_some();
 
     // 🔒 SECURITY: Apply different limits based on authentication status
     match claims {
@@ -129,8 +230,8 @@ pub async fn search_marketplace(
                 crate::utils::log_sanitizer::sanitize_ip_for_log(&addr.ip())
             );
 
-            let results = inventory_service.search_marketplace(request).await?;
-            Ok(Json(results))
+            let results = inventory_service.search_marketplace(request, config.search_engine.as_ref()).await?;
+            Ok(Json(results.into_body(with_facets)?))
         }
         None => {
             // ⚠️  Unauthenticated user - limited access
@@ -147,12 +248,23 @@ pub async fn search_marketplace(
                 UNAUTHENTICATED_LIMIT
             );
 
-            let results = inventory_service.search_marketplace(request).await?;
-            Ok(Json(results))
+            let results = inventory_service.search_marketplace(request, config.search_engine.as_ref()).await?;
+            Ok(Json(results.into_body(with_facets)?))
         }
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/public/expiry-alerts",
+    tag = "inventory",
+    params(
+        crate::models::inventory::ExpiryAlertRequest,
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<crate::models::inventory::ExpiryAlert>),
+    )
+)]
 pub async fn get_expiry_alerts(
     State(config): State<AppConfig>,
     Query(request): Query<crate::models::inventory::ExpiryAlertRequest>,
@@ -0,0 +1,120 @@
+/// Legal Hold REST API Handlers (admin only)
+///
+/// Place and release legal holds on a user (company), a set of transactions or all
+/// data of a category. Held data is exempt from retention purges and held users cannot
+/// be deleted until the hold is released.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use uuid::Uuid;
+use validator::Validate;
+use crate::{
+    config::AppConfig,
+    middleware::{error_handling::{AppError, Result}, Claims},
+    models::legal_hold::*,
+    services::LegalHoldService,
+};
+
+/// GET /api/admin/legal-holds
+/// Active legal holds (`?include_released=true` for all, `?user_id=` for one user)
+#[utoipa::path(
+    get,
+    path = "/api/admin/legal-holds",
+    tag = "legal_holds",
+    summary = "Active legal holds (`?include_released=true` for all, `?user_id=` for one user)",
+    params(
+        LegalHoldQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<LegalHold>),
+    )
+)]
+pub async fn list_legal_holds(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<LegalHoldQuery>,
+) -> Result<Json<Vec<LegalHold>>> {
+    crate::require_admin!(claims);
+
+    let service = LegalHoldService::new(config.database_pool.clone());
+    Ok(Json(service.list(&query).await?))
+}
+
+/// GET /api/admin/legal-holds/:id
+#[utoipa::path(
+    get,
+    path = "/api/admin/legal-holds/{id}",
+    tag = "legal_holds",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = LegalHold),
+    )
+)]
+pub async fn get_legal_hold(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(hold_id): Path<Uuid>,
+) -> Result<Json<LegalHold>> {
+    crate::require_admin!(claims);
+
+    let service = LegalHoldService::new(config.database_pool.clone());
+    Ok(Json(service.get(hold_id).await?))
+}
+
+/// POST /api/admin/legal-holds
+/// Place a hold on `user_id`, on `transaction_ids`, or (neither) on all data,
+/// optionally limited to one `category`
+#[utoipa::path(
+    post,
+    path = "/api/admin/legal-holds",
+    tag = "legal_holds",
+    summary = "Place a hold on `user_id`, on `transaction_ids`, or (neither) on all data,",
+    request_body = CreateLegalHoldRequest,
+    responses(
+        (status = 201, description = "Created", body = LegalHold),
+    )
+)]
+pub async fn create_legal_hold(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<CreateLegalHoldRequest>,
+) -> Result<(StatusCode, Json<LegalHold>)> {
+    crate::require_admin!(claims);
+    request.validate().map_err(AppError::Validation)?;
+
+    let service = LegalHoldService::new(config.database_pool.clone());
+    let hold = service.create(request, claims.user_id).await?;
+    Ok((StatusCode::CREATED, Json(hold)))
+}
+
+/// POST /api/admin/legal-holds/:id/release
+#[utoipa::path(
+    post,
+    path = "/api/admin/legal-holds/{id}/release",
+    tag = "legal_holds",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    request_body = ReleaseLegalHoldRequest,
+    responses(
+        (status = 200, description = "Success", body = LegalHold),
+    )
+)]
+pub async fn release_legal_hold(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(hold_id): Path<Uuid>,
+    Json(request): Json<ReleaseLegalHoldRequest>,
+) -> Result<Json<LegalHold>> {
+    crate::require_admin!(claims);
+    request.validate().map_err(AppError::Validation)?;
+
+    let service = LegalHoldService::new(config.database_pool.clone());
+    let hold = service.release(hold_id, request, claims.user_id).await?;
+    Ok(Json(hold))
+}
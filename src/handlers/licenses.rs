@@ -0,0 +1,255 @@
+/// License Registry REST API Handlers
+///
+/// Companies register their licenses and certifications (type, number, issuing
+/// authority, expiry) with the scanned document; admins verify them. Expired verified
+/// licenses suspend the company's listing rights until a renewal is verified.
+
+use axum::{
+    extract::{Multipart, Path, Query, State},
+    http::{header, HeaderValue, StatusCode},
+    response::Response,
+    Extension, Json,
+};
+use uuid::Uuid;
+use validator::Validate;
+use crate::{
+    config::AppConfig,
+    middleware::{error_handling::{AppError, Result}, Claims},
+    models::license::*,
+    services::LicenseService,
+};
+
+/// Multipart text fields accepted alongside the `file` field
+const LICENSE_FIELDS: [&str; 6] = [
+    "license_type",
+    "license_number",
+    "issuing_authority",
+    "country_code",
+    "issued_on",
+    "expires_on",
+];
+
+/// GET /api/licenses
+/// The user's licenses and whether they may list inventory
+#[utoipa::path(
+    get,
+    path = "/api/licenses",
+    tag = "licenses",
+    summary = "The user's licenses and whether they may list inventory",
+    responses(
+        (status = 200, description = "Success", body = LicenseRegistryResponse),
+    )
+)]
+pub async fn get_license_registry(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<LicenseRegistryResponse>> {
+    let service = LicenseService::new(config.database_pool.clone(), &config.file_storage_path)?;
+    Ok(Json(service.registry(claims.user_id).await?))
+}
+
+/// POST /api/licenses
+/// Register a license (multipart fields: `file` plus `license_type`, `license_number`,
+/// `issuing_authority`, optional `country_code`, `issued_on`, `expires_on` as YYYY-MM-DD)
+#[utoipa::path(
+    post,
+    path = "/api/licenses",
+    tag = "licenses",
+    summary = "Register a license (multipart fields: `file` plus `license_type`, `license_number`,",
+    request_body(content = crate::openapi::LicenseUploadForm, content_type = "multipart/form-data"),
+    responses(
+        (status = 201, description = "Created", body = BusinessLicense),
+    )
+)]
+pub async fn upload_license(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    mut multipart: Multipart,
+) -> Result<(StatusCode, Json<BusinessLicense>)> {
+    let mut file_data: Option<Vec<u8>> = None;
+    let mut filename: Option<String> = None;
+    let mut fields = serde_json::Map::new();
+
+    while let Some(field) = multipart.next_field().await.map_err(|e| {
+        AppError::InvalidInput(format!("Invalid multipart data: {}", e))
+    })? {
+        let field_name = field.name().unwrap_or("").to_string();
+
+        if field_name == "file" {
+            filename = field.file_name().map(|s| s.to_string());
+            file_data = Some(field.bytes().await.map_err(|e| {
+                AppError::InvalidInput(format!("Failed to read file: {}", e))
+            })?.to_vec());
+        } else if LICENSE_FIELDS.contains(&field_name.as_str()) {
+            let text = field.text().await.map_err(|e| {
+                AppError::InvalidInput(format!("Invalid {}: {}", field_name, e))
+            })?;
+            if !text.trim().is_empty() {
+                fields.insert(field_name, serde_json::Value::String(text.trim().to_string()));
+            }
+        }
+    }
+
+    let file_data = file_data.ok_or_else(|| AppError::InvalidInput("No license document provided".to_string()))?;
+    let filename = filename.unwrap_or_else(|| "license".to_string());
+
+    let request: CreateLicenseRequest = serde_json::from_value(serde_json::Value::Object(fields))
+        .map_err(|e| AppError::InvalidInput(format!("Invalid license details: {}", e)))?;
+    request.validate().map_err(AppError::Validation)?;
+
+    let service = LicenseService::new(config.database_pool.clone(), &config.file_storage_path)?;
+    let license = service.upload(claims.user_id, request, &filename, &file_data).await?;
+
+    tracing::info!(
+        "Audit: User {} registered {} license {}",
+        claims.user_id,
+        license.license_type,
+        license.id
+    );
+
+    Ok((StatusCode::CREATED, Json(license)))
+}
+
+/// GET /api/licenses/:id
+#[utoipa::path(
+    get,
+    path = "/api/licenses/{id}",
+    tag = "licenses",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = BusinessLicense),
+    )
+)]
+pub async fn get_license(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(license_id): Path<Uuid>,
+) -> Result<Json<BusinessLicense>> {
+    let service = LicenseService::new(config.database_pool.clone(), &config.file_storage_path)?;
+    Ok(Json(service.get(license_id, claims.user_id, claims.is_admin()).await?))
+}
+
+/// GET /api/licenses/:id/document
+/// The attached license document (owner or admin)
+#[utoipa::path(
+    get,
+    path = "/api/licenses/{id}/document",
+    tag = "licenses",
+    summary = "The attached license document (owner or admin)",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "The uploaded license document", body = crate::openapi::FileDownload, content_type = "application/octet-stream"),
+    )
+)]
+pub async fn get_license_document(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(license_id): Path<Uuid>,
+) -> Result<Response> {
+    let service = LicenseService::new(config.database_pool.clone(), &config.file_storage_path)?;
+    let (license, data) = service.document(license_id, claims.user_id, claims.is_admin()).await?;
+
+    let mut response = Response::new(data.into());
+    let headers = response.headers_mut();
+
+    if let Ok(value) = HeaderValue::from_str(&license.document_content_type) {
+        headers.insert(header::CONTENT_TYPE, value);
+    }
+    if let Ok(value) = HeaderValue::from_str(&format!("inline; filename=\"{}\"", license.document_filename.replace('"', ""))) {
+        headers.insert(header::CONTENT_DISPOSITION, value);
+    }
+    headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("private, no-store"));
+
+    Ok(response)
+}
+
+/// DELETE /api/licenses/:id
+/// Remove a pending or rejected license
+#[utoipa::path(
+    delete,
+    path = "/api/licenses/{id}",
+    tag = "licenses",
+    summary = "Remove a pending or rejected license",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 204, description = "No content"),
+    )
+)]
+pub async fn delete_license(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(license_id): Path<Uuid>,
+) -> Result<StatusCode> {
+    let service = LicenseService::new(config.database_pool.clone(), &config.file_storage_path)?;
+    service.delete(license_id, claims.user_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /api/admin/licenses
+/// Review queue and expiry overview (`?status=pending`, `?expiring_within_days=30`, ...)
+#[utoipa::path(
+    get,
+    path = "/api/admin/licenses",
+    tag = "licenses",
+    summary = "Review queue and expiry overview (`?status=pending`, `?expiring_within_days=30`, ...)",
+    params(
+        LicenseQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<BusinessLicense>),
+    )
+)]
+pub async fn list_licenses(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<LicenseQuery>,
+) -> Result<Json<Vec<BusinessLicense>>> {
+    crate::require_admin!(claims);
+
+    let service = LicenseService::new(config.database_pool.clone(), &config.file_storage_path)?;
+    Ok(Json(service.list(&query).await?))
+}
+
+/// POST /api/admin/licenses/:id/review
+/// Verify or reject a pending license, or revoke a verified one
+#[utoipa::path(
+    post,
+    path = "/api/admin/licenses/{id}/review",
+    tag = "licenses",
+    summary = "Verify or reject a pending license, or revoke a verified one",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    request_body = ReviewLicenseRequest,
+    responses(
+        (status = 200, description = "Success", body = BusinessLicense),
+    )
+)]
+pub async fn review_license(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(license_id): Path<Uuid>,
+    Json(request): Json<ReviewLicenseRequest>,
+) -> Result<Json<BusinessLicense>> {
+    crate::require_admin!(claims);
+    request.validate().map_err(AppError::Validation)?;
+
+    let service = LicenseService::new(config.database_pool.clone(), &config.file_storage_path)?;
+    let license = service.review(license_id, claims.user_id, request).await?;
+
+    tracing::info!(
+        "Audit: Admin {} set license {} of user {} to {}",
+        claims.user_id,
+        license.id,
+        license.user_id,
+        license.status
+    );
+
+    Ok(Json(license))
+}
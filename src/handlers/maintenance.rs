@@ -0,0 +1,76 @@
+/// REST API handlers for maintenance mode (superadmin)
+
+use axum::{
+    extract::State,
+    Extension,
+    Json,
+};
+use crate::{
+    config::AppConfig,
+    middleware::{error_handling::Result, Claims},
+    models::maintenance::*,
+    services::{
+        comprehensive_audit_service::{ActionResult, AuditLogEntry, ComprehensiveAuditService, EventCategory, Severity},
+        MaintenanceService,
+    },
+};
+
+/// GET /api/admin/maintenance
+/// Whether maintenance is on and how much work is still running (drain status)
+#[utoipa::path(
+    get,
+    path = "/api/admin/maintenance",
+    tag = "maintenance",
+    summary = "Whether maintenance is on and how much work is still running (drain status)",
+    responses(
+        (status = 200, description = "Success", body = MaintenanceStatus),
+    )
+)]
+pub async fn get_maintenance_status(
+    State(config): State<AppConfig>,
+) -> Result<Json<MaintenanceStatus>> {
+    let service = MaintenanceService::new(config.database_pool.clone());
+    Ok(Json(service.status().await?))
+}
+
+/// PUT /api/admin/maintenance
+/// Switch maintenance on or off on every instance
+#[utoipa::path(
+    put,
+    path = "/api/admin/maintenance",
+    tag = "maintenance",
+    summary = "Switch maintenance on or off on every instance",
+    request_body = UpdateMaintenanceRequest,
+    responses(
+        (status = 200, description = "Success", body = MaintenanceStatus),
+    )
+)]
+pub async fn update_maintenance(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<UpdateMaintenanceRequest>,
+) -> Result<Json<MaintenanceStatus>> {
+    let service = MaintenanceService::new(config.database_pool.clone());
+    let status = service.update(&request, claims.user_id).await?;
+
+    ComprehensiveAuditService::new(config.database_pool.clone()).log(AuditLogEntry {
+        event_type: "admin_maintenance_mode".to_string(),
+        event_category: EventCategory::Admin,
+        severity: Severity::Warning,
+        actor_user_id: Some(claims.user_id),
+        actor_type: "user".to_string(),
+        resource_type: Some("maintenance".to_string()),
+        action: if request.enabled { "enable_maintenance".to_string() } else { "disable_maintenance".to_string() },
+        action_result: ActionResult::Success,
+        event_data: serde_json::json!({
+            "enabled": status.enabled,
+            "retry_after_seconds": status.retry_after_seconds,
+            "message": status.message,
+            "running_background_jobs": status.drain.running_background_jobs,
+        }),
+        compliance_tags: vec!["admin".to_string(), "configuration".to_string()],
+        ..Default::default()
+    }).await?;
+
+    Ok(Json(status))
+}
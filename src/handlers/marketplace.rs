@@ -7,12 +7,22 @@ use validator::Validate;
 use crate::{
     models::{
         marketplace::{CreateInquiryRequest, UpdateInquiryRequest, CreateTransactionRequest},
+        regulatory_rule::EvaluationContext,
     },
     services::MarketplaceService,
     middleware::{error_handling::Result, Claims},
     config::AppConfig,
 };
 
+#[utoipa::path(
+    post,
+    path = "/api/marketplace/inquiries",
+    tag = "marketplace",
+    request_body = CreateInquiryRequest,
+    responses(
+        (status = 200, description = "Success", body = crate::models::marketplace::InquiryResponse),
+    )
+)]
 pub async fn create_inquiry(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -21,60 +31,28 @@ pub async fn create_inquiry(
     request.validate()
         .map_err(|e| crate::middleware::error_handling::AppError::Validation(e))?;
 
-    let inventory_repo = crate::repositories::InventoryRepository::new(config.database_pool.clone());
-    let user_repo = crate::repositories::UserRepository::new(config.database_pool.clone(), &config.encryption_key)?;
-
-    // Get inventory to find seller and product name
-    let inventory = inventory_repo
-        .find_by_id(request.inventory_id)
-        .await?
-        .ok_or(crate::middleware::error_handling::AppError::NotFound("Inventory not found".to_string()))?;
-
-    let seller_id = inventory.user_id;
-
-    // Get buyer company name
-    let buyer = user_repo.find_by_id(claims.user_id).await?
-        .ok_or(crate::middleware::error_handling::AppError::NotFound("User not found".to_string()))?;
-
-    // Get product name from pharmaceuticals
-    let pharma_repo = crate::repositories::PharmaceuticalRepository::new(config.database_pool.clone());
-    let pharma = pharma_repo.find_by_id(inventory.pharmaceutical_id).await?
-        .ok_or(crate::middleware::error_handling::AppError::NotFound("Product not found".to_string()))?;
-
-    let marketplace_service = MarketplaceService::new(
-        crate::repositories::MarketplaceRepository::new(config.database_pool.clone()),
-        inventory_repo,
-        user_repo,
-        pharma_repo,
-        crate::services::InventoryService::new(
-            crate::repositories::InventoryRepository::new(config.database_pool.clone()),
-            crate::repositories::PharmaceuticalRepository::new(config.database_pool.clone()),
-        ),
-    );
-
-    let inquiry = marketplace_service.create_inquiry(request.clone(), claims.user_id).await?;
-
-    // Create notification for seller
-    let notification_service = crate::services::NotificationService::new(config.database_pool.clone());
-    let product_name = format!("{} {}", pharma.brand_name, pharma.generic_name);
-    let alert_payload = crate::models::alerts::AlertPayload::new_inquiry(
-        seller_id,
+    let inquiry = crate::services::place_inquiry(
+        &config.database_pool,
+        &config.encryption_key,
+        request,
         claims.user_id,
-        &buyer.company_name,
-        &product_name,
-        request.quantity_requested,
-        inquiry.id,
-        request.inventory_id,
-    );
-
-    // Fire and forget - don't fail inquiry creation if notification fails
-    if let Err(e) = notification_service.create_alert(alert_payload).await {
-        tracing::warn!("Failed to create inquiry notification: {}", e);
-    }
+    )
+    .await?;
 
     Ok(Json(inquiry))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/marketplace/inquiries/{id}",
+    tag = "marketplace",
+    params(
+        ("id" = uuid::Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = crate::models::marketplace::InquiryResponse),
+    )
+)]
 pub async fn get_inquiry(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -95,6 +73,18 @@ pub async fn get_inquiry(
     Ok(Json(inquiry))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/marketplace/inquiries/buyer",
+    tag = "marketplace",
+    params(
+        ("limit" = Option<i64>, Query),
+        ("offset" = Option<i64>, Query),
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<crate::models::marketplace::InquiryResponse>),
+    )
+)]
 pub async fn get_buyer_inquiries(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -118,6 +108,18 @@ pub async fn get_buyer_inquiries(
     Ok(Json(inquiries))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/marketplace/inquiries/seller",
+    tag = "marketplace",
+    params(
+        ("limit" = Option<i64>, Query),
+        ("offset" = Option<i64>, Query),
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<crate::models::marketplace::InquiryResponse>),
+    )
+)]
 pub async fn get_seller_inquiries(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -141,6 +143,18 @@ pub async fn get_seller_inquiries(
     Ok(Json(inquiries))
 }
 
+#[utoipa::path(
+    put,
+    path = "/api/marketplace/inquiries/{id}/status",
+    tag = "marketplace",
+    params(
+        ("id" = uuid::Uuid, Path),
+    ),
+    request_body = UpdateInquiryRequest,
+    responses(
+        (status = 200, description = "Success", body = crate::models::marketplace::InquiryResponse),
+    )
+)]
 pub async fn update_inquiry_status(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -165,6 +179,15 @@ pub async fn update_inquiry_status(
     Ok(Json(inquiry))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/marketplace/transactions",
+    tag = "marketplace",
+    request_body = CreateTransactionRequest,
+    responses(
+        (status = 200, description = "Success", body = crate::models::marketplace::TransactionResponse),
+    )
+)]
 pub async fn create_transaction(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -207,10 +230,52 @@ pub async fn create_transaction(
         ),
     );
 
-    let transaction = marketplace_service.create_transaction(request, seller_id, buyer_id).await?;
+    let regulatory_rules = crate::services::RegulatoryRulesService::new(config.database_pool.clone());
+    let evaluation = regulatory_rules
+        .enforce(EvaluationContext::Transaction, inquiry.inventory_id, buyer_id)
+        .await?;
+
+    let mut transaction = marketplace_service.create_transaction(request, seller_id, buyer_id).await?;
+
+    if let Err(e) = regulatory_rules
+        .record(EvaluationContext::Transaction, &evaluation, Some(transaction.inquiry_id), Some(transaction.id))
+        .await
+    {
+        tracing::warn!("Failed to record regulatory evaluation of transaction {}: {}", transaction.id, e);
+    }
+    transaction.regulatory_requirements = Some(evaluation.requirements);
+
+    crate::services::UsageMeteringService::new(config.database_pool.clone())
+        .record(seller_id, crate::models::billing::BillingMeter::Transactions, 1)
+        .await;
+
+    // Emit the EDI purchase order in the background when the seller trades over EDI
+    let edi_pool = config.database_pool.clone();
+    let transaction_id = transaction.id;
+    tokio::spawn(async move {
+        let result = match crate::services::edi::EdiService::new(edi_pool) {
+            Ok(service) => service.send_purchase_order(transaction_id).await.map(|_| ()),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            tracing::error!("Failed to emit EDI purchase order for transaction {}: {}", transaction_id, e);
+        }
+    });
+
     Ok(Json(transaction))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/marketplace/transactions/{id}",
+    tag = "marketplace",
+    params(
+        ("id" = uuid::Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = crate::models::marketplace::TransactionResponse),
+    )
+)]
 pub async fn get_transaction(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -231,6 +296,18 @@ pub async fn get_transaction(
     Ok(Json(transaction))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/marketplace/transactions/my",
+    tag = "marketplace",
+    params(
+        ("limit" = Option<i64>, Query),
+        ("offset" = Option<i64>, Query),
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<crate::models::marketplace::TransactionResponse>),
+    )
+)]
 pub async fn get_user_transactions(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -254,6 +331,17 @@ pub async fn get_user_transactions(
     Ok(Json(transactions))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/marketplace/transactions/{id}/complete",
+    tag = "marketplace",
+    params(
+        ("id" = uuid::Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = crate::models::marketplace::TransactionResponse),
+    )
+)]
 pub async fn complete_transaction(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -270,10 +358,66 @@ pub async fn complete_transaction(
         ),
     );
 
+    // Screen both parties against the sanctions lists; strong matches hold the transaction
+    crate::services::SanctionsScreeningService::new(config.database_pool.clone(), &config.encryption_key)?
+        .screen_transaction(transaction_id, claims.user_id)
+        .await?;
+
     let transaction = marketplace_service.complete_transaction(transaction_id, claims.user_id).await?;
+
+    // Book the sale/purchase in the parties' ERPs off the request path
+    let erp_pool = config.database_pool.clone();
+    tokio::spawn(async move {
+        let sync_service = crate::services::erp::ErpSyncService::new(erp_pool);
+        if let Err(e) = sync_service.export_transaction_orders(transaction_id).await {
+            tracing::error!("Failed to export ERP orders for transaction {}: {}", transaction_id, e);
+        }
+    });
+
+    // Notify both parties' webhook endpoints
+    for party in [transaction.seller_id, transaction.buyer_id] {
+        crate::services::OutboundWebhookService::emit_in_background(
+            config.database_pool.clone(),
+            Some(party),
+            crate::models::webhook::WebhookEventType::TransactionCompleted,
+            serde_json::json!({
+                "transaction_id": transaction.id,
+                "inquiry_id": transaction.inquiry_id,
+                "role": if party == transaction.seller_id { "seller" } else { "buyer" },
+                "quantity": transaction.quantity,
+                "unit_price": transaction.unit_price,
+                "total_price": transaction.total_price,
+                "transaction_date": transaction.transaction_date,
+            }),
+        );
+    }
+
+    // Invoice the sale in the seller's accounting system (QuickBooks Online / Xero)
+    let accounting_pool = config.database_pool.clone();
+    tokio::spawn(async move {
+        let result = match crate::services::accounting::AccountingService::new(accounting_pool) {
+            Ok(service) => service.export_transaction(transaction_id).await.map(|_| ()),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            tracing::error!("Failed to export invoices for transaction {}: {}", transaction_id, e);
+        }
+    });
+
     Ok(Json(transaction))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/marketplace/transactions/{id}/cancel",
+    tag = "marketplace",
+    params(
+        ("id" = uuid::Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = crate::models::marketplace::TransactionResponse),
+    )
+)]
 pub async fn cancel_transaction(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
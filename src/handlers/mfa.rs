@@ -8,6 +8,7 @@ use axum::{
 };
 use uuid::Uuid;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use crate::{
     config::AppConfig,
     middleware::{error_handling::Result, Claims},
@@ -18,13 +19,13 @@ use crate::{
 // REQUEST/RESPONSE TYPES
 // ============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct StartEnrollmentRequest {
     /// User's password for re-authentication
     pub password: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct StartEnrollmentResponse {
     /// Base32-encoded TOTP secret
     pub secret: String,
@@ -34,7 +35,7 @@ pub struct StartEnrollmentResponse {
     pub backup_codes: Vec<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CompleteEnrollmentRequest {
     /// TOTP code from authenticator app (to verify setup)
     pub totp_code: String,
@@ -46,7 +47,7 @@ pub struct CompleteEnrollmentRequest {
     pub device_name: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct VerifyMfaRequest {
     /// TOTP code or backup code
     pub code: String,
@@ -54,13 +55,13 @@ pub struct VerifyMfaRequest {
     pub trust_device: Option<bool>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct VerifyMfaResponse {
     pub success: bool,
     pub trusted_device_id: Option<Uuid>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct DisableMfaRequest {
     /// User's password for re-authentication
     pub password: String,
@@ -68,7 +69,7 @@ pub struct DisableMfaRequest {
     pub mfa_code: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct MfaStatusResponse {
     pub mfa_enabled: bool,
     pub enrolled_at: Option<String>,
@@ -76,7 +77,7 @@ pub struct MfaStatusResponse {
     pub trusted_devices_count: i32,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct TrustedDevice {
     pub id: Uuid,
     pub device_name: Option<String>,
@@ -92,6 +93,15 @@ pub struct TrustedDevice {
 
 /// GET /api/mfa/status
 /// Get user's MFA status
+#[utoipa::path(
+    get,
+    path = "/api/mfa/status",
+    tag = "mfa",
+    summary = "Get user's MFA status",
+    responses(
+        (status = 200, description = "Success", body = MfaStatusResponse),
+    )
+)]
 pub async fn get_mfa_status(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -122,6 +132,16 @@ pub async fn get_mfa_status(
 
 /// POST /api/mfa/enroll/start
 /// Start MFA enrollment (generate secret and QR code)
+#[utoipa::path(
+    post,
+    path = "/api/mfa/enroll/start",
+    tag = "mfa",
+    summary = "Start MFA enrollment (generate secret and QR code)",
+    request_body = StartEnrollmentRequest,
+    responses(
+        (status = 200, description = "Success", body = StartEnrollmentResponse),
+    )
+)]
 pub async fn start_enrollment(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -164,6 +184,16 @@ pub async fn start_enrollment(
 
 /// POST /api/mfa/enroll/complete
 /// Complete MFA enrollment (verify TOTP code and save)
+#[utoipa::path(
+    post,
+    path = "/api/mfa/enroll/complete",
+    tag = "mfa",
+    summary = "Complete MFA enrollment (verify TOTP code and save)",
+    request_body = CompleteEnrollmentRequest,
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
 pub async fn complete_enrollment(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -203,6 +233,16 @@ pub async fn complete_enrollment(
 
 /// POST /api/mfa/verify
 /// Verify MFA code during login
+#[utoipa::path(
+    post,
+    path = "/api/mfa/verify",
+    tag = "mfa",
+    summary = "Verify MFA code during login",
+    request_body = VerifyMfaRequest,
+    responses(
+        (status = 200, description = "Success", body = VerifyMfaResponse),
+    )
+)]
 pub async fn verify_mfa(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -317,6 +357,16 @@ pub async fn verify_mfa(
 
 /// POST /api/mfa/disable
 /// Disable MFA for user
+#[utoipa::path(
+    post,
+    path = "/api/mfa/disable",
+    tag = "mfa",
+    summary = "Disable MFA for user",
+    request_body = DisableMfaRequest,
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
 pub async fn disable_mfa(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -377,6 +427,15 @@ pub async fn disable_mfa(
 
 /// GET /api/mfa/trusted-devices
 /// Get list of trusted devices
+#[utoipa::path(
+    get,
+    path = "/api/mfa/trusted-devices",
+    tag = "mfa",
+    summary = "Get list of trusted devices",
+    responses(
+        (status = 200, description = "Success", body = Vec<TrustedDevice>),
+    )
+)]
 pub async fn get_trusted_devices(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -414,6 +473,18 @@ pub async fn get_trusted_devices(
 
 /// DELETE /api/mfa/trusted-devices/:id
 /// Revoke a trusted device
+#[utoipa::path(
+    delete,
+    path = "/api/mfa/trusted-devices/{id}",
+    tag = "mfa",
+    summary = "Revoke a trusted device",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
 pub async fn revoke_trusted_device(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -16,6 +16,40 @@ pub mod regulatory_documents;
 pub mod erp_integration;
 pub mod erp_ai_integration;
 pub mod oauth;
+pub mod product_images;
+pub mod catalog_subscriptions;
+pub mod regulator_catalogs;
+pub mod sync_sources;
+pub mod edi;
+pub mod accounting;
+pub mod semantic_search;
+pub mod pricing_suggestions;
+pub mod ai_quota;
+pub mod regulatory_knowledge_base;
+pub mod anomalies;
+pub mod webhooks;
+pub mod announcements;
+pub mod dscsa;
+pub mod product_verification;
+pub mod data_retention;
+pub mod legal_holds;
+pub mod temperature_excursions;
+pub mod licenses;
+pub mod sanctions;
+pub mod regulatory_rules;
+pub mod audit_chain;
+pub mod document_batches;
+pub mod background_jobs;
+pub mod admin_ops;
+pub mod billing;
+pub mod settings;
+pub mod admin_analytics;
+pub mod maintenance;
+pub mod payload_captures;
+pub mod data_exports;
+pub mod graphql;
+pub mod fhir;
+pub mod batch_operations;
 
 pub use admin::*;
 pub use admin_security::*;
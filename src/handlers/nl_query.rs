@@ -1,20 +1,32 @@
 /// REST API handlers for Natural Language Query system
 
 use axum::{
-    extract::{State, Path},
+    extract::{State, Path, Query},
+    http::{header, HeaderValue, StatusCode},
+    response::Response,
     Extension,
     Json,
 };
 use uuid::Uuid;
 use crate::{
     config::AppConfig,
-    middleware::{error_handling::Result, Claims},
+    middleware::{error_handling::{AppError, Result}, Claims},
     models::nl_query::*,
-    services::NlQueryService,
+    services::{NlQueryService, NlReportService},
 };
 
 /// POST /api/nl-query/execute
 /// Execute a natural language query
+#[utoipa::path(
+    post,
+    path = "/api/nl-query/execute",
+    tag = "nl_query",
+    summary = "Execute a natural language query",
+    request_body = ExecuteQueryRequest,
+    responses(
+        (status = 200, description = "Success", body = QueryResponse),
+    )
+)]
 pub async fn execute_query(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -38,6 +50,19 @@ pub async fn execute_query(
 
 /// GET /api/nl-query/session/:id
 /// Get query session details
+#[utoipa::path(
+    get,
+    path = "/api/nl-query/session/{id}",
+    tag = "nl_query",
+    operation_id = "nl_query_get_session",
+    summary = "Get query session details",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = QueryResponse),
+    )
+)]
 pub async fn get_session(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -63,6 +88,15 @@ pub async fn get_session(
 
 /// GET /api/nl-query/history
 /// Get user's query history
+#[utoipa::path(
+    get,
+    path = "/api/nl-query/history",
+    tag = "nl_query",
+    summary = "Get user's query history",
+    responses(
+        (status = 200, description = "Success", body = Vec<QueryHistoryItem>),
+    )
+)]
 pub async fn get_history(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -91,6 +125,16 @@ pub async fn get_history(
 
 /// POST /api/nl-query/favorites
 /// Save query as favorite
+#[utoipa::path(
+    post,
+    path = "/api/nl-query/favorites",
+    tag = "nl_query",
+    summary = "Save query as favorite",
+    request_body = SaveFavoriteRequest,
+    responses(
+        (status = 200, description = "Success", body = FavoriteResponse),
+    )
+)]
 pub async fn save_favorite(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -114,6 +158,15 @@ pub async fn save_favorite(
 
 /// GET /api/nl-query/favorites
 /// Get user's favorite queries
+#[utoipa::path(
+    get,
+    path = "/api/nl-query/favorites",
+    tag = "nl_query",
+    summary = "Get user's favorite queries",
+    responses(
+        (status = 200, description = "Success", body = Vec<FavoriteResponse>),
+    )
+)]
 pub async fn get_favorites(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -135,6 +188,16 @@ pub async fn get_favorites(
 
 /// GET /api/nl-query/quota
 /// Get user's NL query quota status
+#[utoipa::path(
+    get,
+    path = "/api/nl-query/quota",
+    tag = "nl_query",
+    operation_id = "nl_query_get_quota",
+    summary = "Get user's NL query quota status",
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
 pub async fn get_quota(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -146,10 +209,191 @@ pub async fn get_quota(
 
     let service = NlQueryService::new(config.database_pool.clone(), claude_api_key);
     let (limit, used, remaining) = service.get_quota_status(claims.user_id).await?;
+    let usage = crate::services::AiQuotaService::new(config.database_pool.clone())
+        .get_usage(claims.user_id)
+        .await?;
 
     Ok(Json(serde_json::json!({
         "query_limit": limit,
         "queries_used": used,
-        "queries_remaining": remaining
+        "queries_remaining": remaining,
+        "usage": usage
     })))
 }
+
+// ============================================================================
+// Exports and Scheduled Reports
+// ============================================================================
+
+/// GET /api/nl-query/session/:id/export?format=csv|xlsx
+/// Download the results of a query session
+#[utoipa::path(
+    get,
+    path = "/api/nl-query/session/{id}/export",
+    tag = "nl_query",
+    summary = "Download the results of a query session",
+    params(
+        ("id" = Uuid, Path),
+        ExportQueryParams,
+    ),
+    responses(
+        (status = 200, description = "The results as CSV or XLSX", body = crate::openapi::FileDownload, content_type = "text/csv"),
+    )
+)]
+pub async fn export_session(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(session_id): Path<Uuid>,
+    Query(params): Query<ExportQueryParams>,
+) -> Result<Response> {
+    let format = ReportFormat::parse(params.format.as_deref()).map_err(AppError::BadRequest)?;
+    let service = report_service(&config)?;
+    let (filename, data) = service.export_session(claims.user_id, session_id, format).await?;
+
+    Ok(file_response(format, &filename, data))
+}
+
+/// POST /api/nl-query/reports/schedules
+/// Schedule a saved favorite to run daily or weekly
+#[utoipa::path(
+    post,
+    path = "/api/nl-query/reports/schedules",
+    tag = "nl_query",
+    summary = "Schedule a saved favorite to run daily or weekly",
+    request_body = CreateReportScheduleRequest,
+    responses(
+        (status = 201, description = "Created", body = NlReportSchedule),
+    )
+)]
+pub async fn create_report_schedule(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<CreateReportScheduleRequest>,
+) -> Result<(StatusCode, Json<NlReportSchedule>)> {
+    let schedule = report_service(&config)?.create_schedule(claims.user_id, &request).await?;
+    Ok((StatusCode::CREATED, Json(schedule)))
+}
+
+/// GET /api/nl-query/reports/schedules
+#[utoipa::path(
+    get,
+    path = "/api/nl-query/reports/schedules",
+    tag = "nl_query",
+    responses(
+        (status = 200, description = "Success", body = Vec<NlReportSchedule>),
+    )
+)]
+pub async fn list_report_schedules(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Vec<NlReportSchedule>>> {
+    Ok(Json(report_service(&config)?.list_schedules(claims.user_id).await?))
+}
+
+/// PUT /api/nl-query/reports/schedules/:id
+/// Change timing, format or delivery, or pause/resume with `is_active`
+#[utoipa::path(
+    put,
+    path = "/api/nl-query/reports/schedules/{id}",
+    tag = "nl_query",
+    summary = "Change timing, format or delivery, or pause/resume with `is_active`",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    request_body = UpdateReportScheduleRequest,
+    responses(
+        (status = 200, description = "Success", body = NlReportSchedule),
+    )
+)]
+pub async fn update_report_schedule(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(schedule_id): Path<Uuid>,
+    Json(request): Json<UpdateReportScheduleRequest>,
+) -> Result<Json<NlReportSchedule>> {
+    let schedule = report_service(&config)?.update_schedule(claims.user_id, schedule_id, &request).await?;
+    Ok(Json(schedule))
+}
+
+/// DELETE /api/nl-query/reports/schedules/:id
+#[utoipa::path(
+    delete,
+    path = "/api/nl-query/reports/schedules/{id}",
+    tag = "nl_query",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 204, description = "No content"),
+    )
+)]
+pub async fn delete_report_schedule(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(schedule_id): Path<Uuid>,
+) -> Result<StatusCode> {
+    report_service(&config)?.delete_schedule(claims.user_id, schedule_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /api/nl-query/reports/files?schedule_id=&limit=
+/// History of generated (and skipped) report runs
+#[utoipa::path(
+    get,
+    path = "/api/nl-query/reports/files",
+    tag = "nl_query",
+    summary = "History of generated (and skipped) report runs",
+    params(
+        ReportFilesQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<NlReportFile>),
+    )
+)]
+pub async fn list_report_files(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<ReportFilesQuery>,
+) -> Result<Json<Vec<NlReportFile>>> {
+    Ok(Json(report_service(&config)?.list_files(claims.user_id, &query).await?))
+}
+
+/// GET /api/nl-query/reports/files/:id/download
+#[utoipa::path(
+    get,
+    path = "/api/nl-query/reports/files/{id}/download",
+    tag = "nl_query",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "The report as CSV or XLSX", body = crate::openapi::FileDownload, content_type = "text/csv"),
+    )
+)]
+pub async fn download_report_file(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(file_id): Path<Uuid>,
+) -> Result<Response> {
+    let (file, data) = report_service(&config)?.download_file(claims.user_id, file_id).await?;
+    let format = ReportFormat::parse(Some(&file.format)).map_err(AppError::BadRequest)?;
+    let filename = file.filename.unwrap_or_else(|| format!("report.{}", format.as_str()));
+
+    Ok(file_response(format, &filename, data))
+}
+
+fn report_service(config: &AppConfig) -> Result<NlReportService> {
+    NlReportService::new(config.database_pool.clone(), &config.file_storage_path, &config.encryption_key)
+}
+
+fn file_response(format: ReportFormat, filename: &str, data: Vec<u8>) -> Response {
+    let mut response = Response::new(data.into());
+    let headers = response.headers_mut();
+
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(format.content_type()));
+    if let Ok(value) = HeaderValue::from_str(&format!("attachment; filename=\"{}\"", filename)) {
+        headers.insert(header::CONTENT_DISPOSITION, value);
+    }
+
+    response
+}
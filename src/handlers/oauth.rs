@@ -12,12 +12,13 @@ use axum::{
 };
 use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 use std::net::SocketAddr;
 use uuid::Uuid;
 
 use crate::{
     config::{AppConfig, oauth::{OAuthConfig, OAuthProvider, OAuthProvidersInfo}},
-    services::{OAuthService, OAuthUserInfo},
+    services::{OAuthService, OAuthUserInfo, UserSuspensionService},
     middleware::{error_handling::{Result, AppError}, Claims, JwtService},
     repositories::UserRepository,
     models::user::UserResponse,
@@ -27,7 +28,8 @@ use crate::{
 // Request/Response Types
 // ============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct OAuthCallbackQuery {
     pub code: String,
     pub state: String,
@@ -37,7 +39,7 @@ pub struct OAuthCallbackQuery {
     pub error_description: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct OAuthStartResponse {
     pub auth_url: String,
     pub state: String,
@@ -50,7 +52,7 @@ pub struct OAuthLoginResponse {
     pub is_new_user: bool,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct OAuthLinkResponse {
     pub success: bool,
     pub provider: String,
@@ -70,6 +72,14 @@ pub struct OAuthErrorResponse {
 
 /// Get list of enabled OAuth providers
 /// GET /api/auth/oauth/providers
+#[utoipa::path(
+    get,
+    path = "/api/auth/oauth/providers",
+    tag = "oauth",
+    responses(
+        (status = 200, description = "Success", body = OAuthProvidersInfo),
+    )
+)]
 pub async fn get_oauth_providers(
     State(_config): State<AppConfig>,
 ) -> Result<Json<OAuthProvidersInfo>> {
@@ -81,6 +91,17 @@ pub async fn get_oauth_providers(
 
 /// Start OAuth flow - redirect to provider
 /// GET /api/auth/oauth/:provider
+#[utoipa::path(
+    get,
+    path = "/api/auth/oauth/{provider}",
+    tag = "oauth",
+    params(
+        ("provider" = String, Path),
+    ),
+    responses(
+        (status = 307, description = "Redirect to the provider's authorization page"),
+    )
+)]
 pub async fn oauth_start(
     State(config): State<AppConfig>,
     Path(provider_name): Path<String>,
@@ -122,6 +143,18 @@ pub async fn oauth_start(
 
 /// OAuth callback - exchange code for tokens
 /// GET /api/auth/oauth/:provider/callback
+#[utoipa::path(
+    get,
+    path = "/api/auth/oauth/{provider}/callback",
+    tag = "oauth",
+    params(
+        ("provider" = String, Path),
+        OAuthCallbackQuery,
+    ),
+    responses(
+        (status = 307, description = "Redirect to the frontend with the session token, or with the error"),
+    )
+)]
 pub async fn oauth_callback(
     State(config): State<AppConfig>,
     Path(provider_name): Path<String>,
@@ -193,6 +226,22 @@ pub async fn oauth_callback(
     let user_repo = UserRepository::new(config.database_pool.clone(), &config.encryption_key)?;
     let (user, is_new_user) = find_or_create_oauth_user(&user_repo, &oauth_user).await?;
 
+    if let Err(e) = UserSuspensionService::new(config.database_pool.clone())
+        .ensure_not_suspended(user.id)
+        .await
+    {
+        if !matches!(e, AppError::Forbidden(_)) {
+            return Err(e);
+        }
+        tracing::warn!(provider = provider.as_str(), user_id = %user.id, "OAuth login refused: account suspended");
+        let error_url = format!(
+            "{}?error=account_suspended&provider={}",
+            oauth_config.frontend_error_url,
+            provider_name
+        );
+        return Ok(Redirect::temporary(&error_url).into_response());
+    }
+
     // Generate JWT using existing JwtService
     let jwt_service = JwtService::new(&config.jwt_secret);
     let token = jwt_service.generate_token(
@@ -234,6 +283,17 @@ pub async fn oauth_callback(
 
 /// Link OAuth provider to existing account (requires auth)
 /// POST /api/auth/oauth/link/:provider
+#[utoipa::path(
+    post,
+    path = "/api/auth/oauth/link/{provider}",
+    tag = "oauth",
+    params(
+        ("provider" = String, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = OAuthStartResponse),
+    )
+)]
 pub async fn oauth_link_start(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -270,6 +330,17 @@ pub async fn oauth_link_start(
 
 /// Unlink OAuth provider from account (requires auth)
 /// POST /api/auth/oauth/unlink/:provider
+#[utoipa::path(
+    post,
+    path = "/api/auth/oauth/unlink/{provider}",
+    tag = "oauth",
+    params(
+        ("provider" = String, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = OAuthLinkResponse),
+    )
+)]
 pub async fn oauth_unlink(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -1,31 +1,66 @@
 use axum::{
+    body::Body,
     extract::{Path, Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
     Json,
     Extension,
 };
 use uuid::Uuid;
 use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
 use crate::{
+    models::catalog_export::{parse_records_range, OpenFdaExportParams},
     models::openfda::{OpenFdaSearchRequest, SyncProgressResponse},
-    services::OpenFdaService,
+    models::openfda_device::{DeviceSearchRequest, OpenFdaDeviceEntry},
+    models::openfda_recall::{OpenFdaRecall, RecallDetailResponse, RecallSearchRequest},
+    models::summary_aggregate::Summarized,
+    services::{CatalogExport, CatalogExportService, OpenFdaService, OpenFdaDeviceService, OpenFdaRecallService},
     middleware::{error_handling::{Result, AppError}, Claims},
-    config::AppConfig,
+    config::{AppConfig, ReadConsistency},
 };
 
 /// Search OpenFDA catalog with autocomplete
+///
+/// Typo-tolerant through the search engine when one is configured. With `facets`,
+/// the results come wrapped with facet counts.
+#[utoipa::path(
+    get,
+    path = "/api/openfda/search",
+    tag = "openfda",
+    operation_id = "openfda_search_catalog",
+    params(
+        OpenFdaSearchRequest,
+    ),
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
 pub async fn search_catalog(
     State(config): State<AppConfig>,
     Query(request): Query<OpenFdaSearchRequest>,
-) -> Result<Json<Vec<crate::models::openfda::OpenFdaCatalogResponse>>> {
+) -> Result<Json<serde_json::Value>> {
     let openfda_service = OpenFdaService::new(
-        crate::repositories::OpenFdaRepository::new(config.database_pool.clone()),
+        crate::repositories::OpenFdaRepository::new(config.read_pool(ReadConsistency::Relaxed)),
     );
 
-    let results = openfda_service.search(request).await?;
-    Ok(Json(results))
+    let with_facets = request.facets.is_some();
+    let results = openfda_service.search(request, config.search_engine.as_ref()).await?;
+    Ok(Json(results.into_body(with_facets)?))
 }
 
 /// Get drug by NDC code
+#[utoipa::path(
+    get,
+    path = "/api/openfda/ndc/{ndc}",
+    tag = "openfda",
+    params(
+        ("ndc" = String, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = Option<crate::models::openfda::OpenFdaCatalogResponse>),
+    )
+)]
 pub async fn get_by_ndc(
     State(config): State<AppConfig>,
     Path(ndc): Path<String>,
@@ -38,37 +73,148 @@ pub async fn get_by_ndc(
     Ok(Json(result))
 }
 
+/// Get the version history of an NDC entry (newest first)
+#[utoipa::path(
+    get,
+    path = "/api/openfda/ndc/{ndc}/history",
+    tag = "openfda",
+    params(
+        ("ndc" = String, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = crate::models::openfda::CatalogHistoryResponse),
+    )
+)]
+pub async fn get_ndc_history(
+    State(config): State<AppConfig>,
+    Path(ndc): Path<String>,
+) -> Result<Json<crate::models::openfda::CatalogHistoryResponse>> {
+    let openfda_service = OpenFdaService::new(
+        crate::repositories::OpenFdaRepository::new(config.database_pool.clone()),
+    );
+
+    let history = openfda_service.get_history(&ndc).await?;
+    Ok(Json(history))
+}
+
+/// GET /api/openfda/export
+/// Stream the catalog as CSV or NDJSON (`format`), filtered by manufacturer, product type,
+/// marketing category, dosage form, DEA schedule and `updated_since`. Interrupted
+/// downloads resume with `after=<last NDC>` or `Range: records=N-`.
+#[utoipa::path(
+    get,
+    path = "/api/openfda/export",
+    tag = "openfda",
+    operation_id = "openfda_export_catalog",
+    summary = "Stream the catalog as CSV or NDJSON (`format`), filtered by manufacturer, product type,",
+    params(
+        OpenFdaExportParams,
+    ),
+    responses(
+        (status = 200, description = "The catalog as CSV or NDJSON", body = String, content_type = "text/csv"),
+        (status = 206, description = "A range of the catalog", body = String, content_type = "text/csv"),
+    )
+)]
+pub async fn export_catalog(
+    State(config): State<AppConfig>,
+    headers: HeaderMap,
+    Query(params): Query<OpenFdaExportParams>,
+) -> Result<Response> {
+    let offset = records_range_offset(&headers)?;
+    let service = CatalogExportService::new(config.read_pool(ReadConsistency::Relaxed));
+    let export = service.export_openfda(params, offset.unwrap_or(0)).await?;
+
+    Ok(export_response(export, "openfda-catalog", offset.is_some()))
+}
+
+/// Records to skip from a `Range: records=N-` header, if the request has one
+pub(crate) fn records_range_offset(headers: &HeaderMap) -> Result<Option<i64>> {
+    match headers.get(header::RANGE).and_then(|v| v.to_str().ok()).and_then(parse_records_range) {
+        Some(offset) => Ok(Some(offset?)),
+        None => Ok(None),
+    }
+}
+
+/// Chunked export response; range requests are answered with 206 and a `Content-Range`
+/// in records, since the byte length of a streamed export isn't known up front
+pub(crate) fn export_response(export: CatalogExport, name: &str, is_range: bool) -> Response {
+    let mut response = Response::new(Body::from_stream(export.stream));
+    let headers = response.headers_mut();
+
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(export.format.content_type()));
+    headers.insert(header::ACCEPT_RANGES, HeaderValue::from_static("records"));
+    if let Ok(value) = HeaderValue::from_str(&format!(
+        "attachment; filename=\"{}.{}\"", name, export.format.extension()
+    )) {
+        headers.insert(header::CONTENT_DISPOSITION, value);
+    }
+    if let Ok(value) = HeaderValue::from_str(&export.total.to_string()) {
+        headers.insert("X-Total-Count", value);
+    }
+
+    if is_range {
+        let last = (export.total - 1).max(export.offset);
+        if let Ok(value) = HeaderValue::from_str(&format!("records {}-{}/{}", export.offset, last, export.total)) {
+            headers.insert(header::CONTENT_RANGE, value);
+        }
+        *response.status_mut() = StatusCode::PARTIAL_CONTENT;
+    }
+
+    response
+}
+
 /// Get catalog statistics
+#[utoipa::path(
+    get,
+    path = "/api/openfda/stats",
+    tag = "openfda",
+    operation_id = "openfda_get_stats",
+    responses(
+        (status = 200, description = "Success", body = crate::services::openfda_service::CatalogStats),
+    )
+)]
 pub async fn get_stats(
     State(config): State<AppConfig>,
 ) -> Result<Json<crate::services::openfda_service::CatalogStats>> {
     let openfda_service = OpenFdaService::new(
-        crate::repositories::OpenFdaRepository::new(config.database_pool.clone()),
+        crate::repositories::OpenFdaRepository::new(config.read_pool(ReadConsistency::Relaxed)),
     );
 
     let stats = openfda_service.get_stats().await?;
     Ok(Json(stats))
 }
 
-/// Get manufacturers from OpenFDA catalog with product counts
+/// Get the top manufacturers of the OpenFDA catalog with product counts, from the
+/// `openfda_manufacturer_counts` summary; Last-Modified is when it was last refreshed
+#[utoipa::path(
+    get,
+    path = "/api/openfda/manufacturers",
+    tag = "openfda",
+    operation_id = "openfda_get_manufacturers",
+    responses(
+        (status = 200, description = "Manufacturer names", body = Vec<String>),
+    )
+)]
 pub async fn get_manufacturers(
     State(config): State<AppConfig>,
-) -> Result<Json<Vec<serde_json::Value>>> {
+) -> Result<Response> {
     use sqlx::{query, Row};
 
+    let pool = config.read_pool(ReadConsistency::Relaxed);
+    let refreshed_at: Option<chrono::DateTime<chrono::Utc>> = sqlx::query_scalar(
+        "SELECT refreshed_at FROM summary_refreshes WHERE view_name = 'openfda_manufacturer_counts'"
+    )
+    .fetch_optional(&pool)
+    .await?;
     let manufacturers = query(
         r#"
-        SELECT
-            labeler_name as manufacturer,
-            COUNT(*) as count
-        FROM openfda_catalog
-        WHERE labeler_name IS NOT NULL AND labeler_name != ''
-        GROUP BY labeler_name
-        ORDER BY count DESC, labeler_name ASC
+        SELECT manufacturer, product_count AS count
+        FROM openfda_manufacturer_counts
+        ORDER BY product_count DESC, manufacturer ASC
         LIMIT 100
         "#
     )
-    .fetch_all(&config.database_pool)
+    .fetch_all(&pool)
     .await?;
 
     let result: Vec<serde_json::Value> = manufacturers.iter().map(|row| {
@@ -78,26 +224,53 @@ pub async fn get_manufacturers(
         })
     }).collect();
 
-    Ok(Json(result))
+    let summary = Summarized { value: result, refreshed_at };
+    let mut response = Json(&summary.value).into_response();
+    if let Some(value) = summary.last_modified().and_then(|value| HeaderValue::from_str(&value).ok()) {
+        response.headers_mut().insert(header::LAST_MODIFIED, value);
+    }
+    Ok(response)
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct TriggerSyncParams {
     pub sync_type: Option<String>,
     pub limit: Option<u64>,
+    pub dry_run: Option<bool>,
 }
 
 /// Trigger sync from OpenFDA API (admin only)
-/// Starts a background sync and returns the sync ID immediately
+/// Starts a background sync and returns the sync ID immediately.
+/// `sync_type=delta` only refetches partitions that changed since the last checkpoint.
+/// `dry_run=true` fetches up to `limit` records and returns a `SyncPreview` of what
+/// would be inserted or updated, without writing anything.
+#[utoipa::path(
+    post,
+    path = "/api/openfda/sync",
+    tag = "openfda",
+    operation_id = "openfda_trigger_sync",
+    params(
+        TriggerSyncParams,
+    ),
+    responses(
+        (status = 200, description = "Sync started; a SyncPreview with dry_run=true", body = TriggerSyncResponse),
+    )
+)]
 pub async fn trigger_sync(
     State(config): State<AppConfig>,
     Extension(_claims): Extension<Claims>,
     Query(params): Query<TriggerSyncParams>,
-) -> Result<Json<TriggerSyncResponse>> {
+) -> Result<Response> {
     let openfda_service = OpenFdaService::new(
         crate::repositories::OpenFdaRepository::new(config.database_pool.clone()),
     );
 
+    if params.dry_run.unwrap_or(false) {
+        let preview = openfda_service.preview_sync(params.limit.map(|l| l as usize)).await?;
+        return Ok(Json(preview).into_response());
+    }
+
     let sync_type = params.sync_type.unwrap_or_else(|| "manual".to_string());
 
     // Start background sync
@@ -106,16 +279,28 @@ pub async fn trigger_sync(
     Ok(Json(TriggerSyncResponse {
         sync_id,
         message: "Sync started in background. Use /api/openfda/sync/{id} to check progress.".to_string(),
-    }))
+    }).into_response())
 }
 
-#[derive(Debug, serde::Serialize)]
+#[derive(Debug, serde::Serialize, ToSchema)]
 pub struct TriggerSyncResponse {
     pub sync_id: Uuid,
     pub message: String,
 }
 
 /// Get sync progress by ID
+#[utoipa::path(
+    get,
+    path = "/api/openfda/sync/{sync_id}",
+    tag = "openfda",
+    operation_id = "openfda_get_sync_progress",
+    params(
+        ("sync_id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = SyncProgressResponse),
+    )
+)]
 pub async fn get_sync_progress(
     State(config): State<AppConfig>,
     Path(sync_id): Path<Uuid>,
@@ -131,6 +316,15 @@ pub async fn get_sync_progress(
 }
 
 /// Get active sync (if any)
+#[utoipa::path(
+    get,
+    path = "/api/openfda/sync/active",
+    tag = "openfda",
+    operation_id = "openfda_get_active_sync",
+    responses(
+        (status = 200, description = "Success", body = Option<SyncProgressResponse>),
+    )
+)]
 pub async fn get_active_sync(
     State(config): State<AppConfig>,
 ) -> Result<Json<Option<SyncProgressResponse>>> {
@@ -142,13 +336,26 @@ pub async fn get_active_sync(
     Ok(Json(active))
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct SyncLogsParams {
     pub limit: Option<i64>,
     pub offset: Option<i64>,
 }
 
 /// Get sync logs history
+#[utoipa::path(
+    get,
+    path = "/api/openfda/sync/logs",
+    tag = "openfda",
+    operation_id = "openfda_get_sync_logs",
+    params(
+        SyncLogsParams,
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<SyncProgressResponse>),
+    )
+)]
 pub async fn get_sync_logs(
     State(config): State<AppConfig>,
     Query(params): Query<SyncLogsParams>,
@@ -162,6 +369,18 @@ pub async fn get_sync_logs(
 }
 
 /// Cancel a running sync
+#[utoipa::path(
+    post,
+    path = "/api/openfda/sync/{sync_id}/cancel",
+    tag = "openfda",
+    operation_id = "openfda_cancel_sync",
+    params(
+        ("sync_id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = CancelSyncResponse),
+    )
+)]
 pub async fn cancel_sync(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -183,13 +402,53 @@ pub async fn cancel_sync(
     }))
 }
 
-#[derive(Debug, serde::Serialize)]
+#[derive(Debug, serde::Serialize, ToSchema)]
 pub struct CancelSyncResponse {
     pub cancelled: bool,
     pub message: String,
 }
 
+/// Resume a failed or interrupted sync from its last checkpoint (admin only)
+#[utoipa::path(
+    post,
+    path = "/api/openfda/sync/{sync_id}/resume",
+    tag = "openfda",
+    params(
+        ("sync_id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = TriggerSyncResponse),
+    )
+)]
+pub async fn resume_sync(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(sync_id): Path<Uuid>,
+) -> Result<Json<TriggerSyncResponse>> {
+    crate::require_admin!(claims);
+
+    let openfda_service = OpenFdaService::new(
+        crate::repositories::OpenFdaRepository::new(config.database_pool.clone()),
+    );
+
+    let sync_id = openfda_service.resume_sync(sync_id, config.database_pool.clone()).await?;
+
+    Ok(Json(TriggerSyncResponse {
+        sync_id,
+        message: "Sync resumed in background. Use /api/openfda/sync/{id} to check progress.".to_string(),
+    }))
+}
+
 /// Check if catalog needs refresh
+#[utoipa::path(
+    get,
+    path = "/api/openfda/refresh-status",
+    tag = "openfda",
+    operation_id = "openfda_check_refresh_status",
+    responses(
+        (status = 200, description = "Success", body = RefreshStatusResponse),
+    )
+)]
 pub async fn check_refresh_status(
     State(config): State<AppConfig>,
 ) -> Result<Json<RefreshStatusResponse>> {
@@ -209,7 +468,7 @@ pub async fn check_refresh_status(
     }))
 }
 
-#[derive(Debug, serde::Serialize)]
+#[derive(Debug, serde::Serialize, ToSchema)]
 pub struct RefreshStatusResponse {
     pub needs_refresh: bool,
     pub is_sync_running: bool,
@@ -217,12 +476,25 @@ pub struct RefreshStatusResponse {
     pub last_sync_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct CleanupParams {
     pub days_to_keep: Option<i32>,
 }
 
 /// Cleanup old sync logs (admin only)
+#[utoipa::path(
+    post,
+    path = "/api/openfda/cleanup",
+    tag = "openfda",
+    operation_id = "openfda_cleanup_sync_logs",
+    params(
+        CleanupParams,
+    ),
+    responses(
+        (status = 200, description = "Success", body = CleanupResponse),
+    )
+)]
 pub async fn cleanup_sync_logs(
     State(config): State<AppConfig>,
     Extension(_claims): Extension<Claims>,
@@ -241,13 +513,22 @@ pub async fn cleanup_sync_logs(
     }))
 }
 
-#[derive(Debug, serde::Serialize)]
+#[derive(Debug, serde::Serialize, ToSchema)]
 pub struct CleanupResponse {
     pub deleted_count: i64,
     pub message: String,
 }
 
 /// Health check endpoint
+#[utoipa::path(
+    get,
+    path = "/api/openfda/health",
+    tag = "openfda",
+    operation_id = "openfda_health_check",
+    responses(
+        (status = 200, description = "Success", body = HealthCheckResponse),
+    )
+)]
 pub async fn health_check(
     State(config): State<AppConfig>,
 ) -> Result<Json<HealthCheckResponse>> {
@@ -266,10 +547,197 @@ pub async fn health_check(
     }))
 }
 
-#[derive(Debug, serde::Serialize)]
+#[derive(Debug, serde::Serialize, ToSchema)]
 pub struct HealthCheckResponse {
     pub status: String,
     pub catalog_size: i64,
     pub last_sync_at: Option<chrono::DateTime<chrono::Utc>>,
     pub sync_in_progress: bool,
 }
+
+// ============================================================================
+// Device (UDI/GUDID) catalog
+// ============================================================================
+
+/// Search the OpenFDA device catalog
+#[utoipa::path(
+    get,
+    path = "/api/openfda/devices/search",
+    tag = "openfda",
+    params(
+        DeviceSearchRequest,
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<OpenFdaDeviceEntry>),
+    )
+)]
+pub async fn search_devices(
+    State(config): State<AppConfig>,
+    Query(request): Query<DeviceSearchRequest>,
+) -> Result<Json<Vec<OpenFdaDeviceEntry>>> {
+    let device_service = OpenFdaDeviceService::new(config.database_pool.clone());
+
+    let results = device_service.search(request).await?;
+    Ok(Json(results))
+}
+
+/// Get device by GUDID primary device identifier
+#[utoipa::path(
+    get,
+    path = "/api/openfda/devices/di/{primary_di}",
+    tag = "openfda",
+    params(
+        ("primary_di" = String, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = Option<OpenFdaDeviceEntry>),
+    )
+)]
+pub async fn get_device_by_di(
+    State(config): State<AppConfig>,
+    Path(primary_di): Path<String>,
+) -> Result<Json<Option<OpenFdaDeviceEntry>>> {
+    let device_service = OpenFdaDeviceService::new(config.database_pool.clone());
+
+    let result = device_service.get_by_primary_di(&primary_di).await?;
+    Ok(Json(result))
+}
+
+/// Trigger device catalog sync (admin only)
+/// Progress is reported through the regular /api/openfda/sync/:id endpoint
+#[utoipa::path(
+    post,
+    path = "/api/openfda/devices/sync",
+    tag = "openfda",
+    params(
+        TriggerSyncParams,
+    ),
+    responses(
+        (status = 200, description = "Success", body = TriggerSyncResponse),
+    )
+)]
+pub async fn trigger_device_sync(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Query(params): Query<TriggerSyncParams>,
+) -> Result<Json<TriggerSyncResponse>> {
+    crate::require_admin!(claims);
+
+    let device_service = OpenFdaDeviceService::new(config.database_pool.clone());
+    let sync_id = device_service
+        .start_background_sync(params.limit.map(|l| l as usize))
+        .await?;
+
+    Ok(Json(TriggerSyncResponse {
+        sync_id,
+        message: "Device sync started in background. Use /api/openfda/sync/{id} to check progress.".to_string(),
+    }))
+}
+
+// ============================================================================
+// Drug enforcement (recalls)
+// ============================================================================
+
+/// Search FDA drug recalls
+#[utoipa::path(
+    get,
+    path = "/api/openfda/recalls",
+    tag = "openfda",
+    params(
+        RecallSearchRequest,
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<OpenFdaRecall>),
+    )
+)]
+pub async fn search_recalls(
+    State(config): State<AppConfig>,
+    Query(request): Query<RecallSearchRequest>,
+) -> Result<Json<Vec<OpenFdaRecall>>> {
+    let recall_service = OpenFdaRecallService::new(config.database_pool.clone());
+
+    let results = recall_service.search(request).await?;
+    Ok(Json(results))
+}
+
+/// Get a recall by recall number, with the catalog entries its NDCs match
+#[utoipa::path(
+    get,
+    path = "/api/openfda/recalls/{recall_number}",
+    tag = "openfda",
+    params(
+        ("recall_number" = String, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = RecallDetailResponse),
+    )
+)]
+pub async fn get_recall(
+    State(config): State<AppConfig>,
+    Path(recall_number): Path<String>,
+) -> Result<Json<RecallDetailResponse>> {
+    let recall_service = OpenFdaRecallService::new(config.database_pool.clone());
+
+    let result = recall_service.get_recall(&recall_number).await?;
+    Ok(Json(result))
+}
+
+/// Recalls affecting a product or package NDC
+#[utoipa::path(
+    get,
+    path = "/api/openfda/recalls/ndc/{ndc}",
+    tag = "openfda",
+    params(
+        ("ndc" = String, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<OpenFdaRecall>),
+    )
+)]
+pub async fn get_recalls_by_ndc(
+    State(config): State<AppConfig>,
+    Path(ndc): Path<String>,
+) -> Result<Json<Vec<OpenFdaRecall>>> {
+    let recall_service = OpenFdaRecallService::new(config.database_pool.clone());
+
+    let results = recall_service.recalls_for_ndc(&ndc).await?;
+    Ok(Json(results))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TriggerRecallSyncParams {
+    /// Re-read the whole enforcement dataset instead of recent reports only
+    pub full: Option<bool>,
+}
+
+/// Trigger recall sync (admin only)
+/// Progress is reported through the regular /api/openfda/sync/:id endpoint
+#[utoipa::path(
+    post,
+    path = "/api/openfda/recalls/sync",
+    tag = "openfda",
+    params(
+        TriggerRecallSyncParams,
+    ),
+    responses(
+        (status = 200, description = "Success", body = TriggerSyncResponse),
+    )
+)]
+pub async fn trigger_recall_sync(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Query(params): Query<TriggerRecallSyncParams>,
+) -> Result<Json<TriggerSyncResponse>> {
+    crate::require_admin!(claims);
+
+    let recall_service = OpenFdaRecallService::new(config.database_pool.clone());
+    let sync_id = recall_service
+        .start_background_sync(params.full.unwrap_or(false))
+        .await?;
+
+    Ok(Json(TriggerSyncResponse {
+        sync_id,
+        message: "Recall sync started in background. Use /api/openfda/sync/{id} to check progress.".to_string(),
+    }))
+}
@@ -0,0 +1,183 @@
+/// REST API handlers for payload capture rules and captures (superadmin)
+
+use axum::{
+    extract::{Path, Query, State},
+    Extension,
+    Json,
+};
+use uuid::Uuid;
+use crate::{
+    config::AppConfig,
+    middleware::{error_handling::Result, Claims},
+    models::payload_capture::*,
+    require_superadmin,
+    services::{
+        comprehensive_audit_service::{ActionResult, AuditLogEntry, ComprehensiveAuditService, EventCategory, Severity},
+        PayloadCaptureService,
+    },
+};
+
+/// GET /api/admin/payload-captures/rules
+/// Recent capture rules, open and closed
+#[utoipa::path(
+    get,
+    path = "/api/admin/payload-captures/rules",
+    tag = "payload_captures",
+    summary = "Recent capture rules, open and closed",
+    responses(
+        (status = 200, description = "Success", body = Vec<PayloadCaptureRule>),
+    )
+)]
+pub async fn list_capture_rules(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Vec<PayloadCaptureRule>>> {
+    require_superadmin!(claims);
+    let service = PayloadCaptureService::new(config.database_pool.clone());
+    Ok(Json(service.list_rules().await?))
+}
+
+/// POST /api/admin/payload-captures/rules
+/// Start capturing one user's and/or one route's traffic for an investigation
+#[utoipa::path(
+    post,
+    path = "/api/admin/payload-captures/rules",
+    tag = "payload_captures",
+    summary = "Start capturing one user's and/or one route's traffic for an investigation",
+    request_body = CreatePayloadCaptureRuleRequest,
+    responses(
+        (status = 200, description = "Success", body = PayloadCaptureRule),
+    )
+)]
+pub async fn create_capture_rule(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<CreatePayloadCaptureRuleRequest>,
+) -> Result<Json<PayloadCaptureRule>> {
+    require_superadmin!(claims);
+    let service = PayloadCaptureService::new(config.database_pool.clone());
+    let rule = service.create_rule(&request, claims.user_id).await?;
+
+    audit(&config, &claims, "admin_payload_capture_opened", "open_capture_rule", rule.id, serde_json::json!({
+        "user_id": rule.user_id,
+        "route": rule.route,
+        "method": rule.method,
+        "reason": rule.reason,
+        "capture_until": rule.capture_until,
+        "retention_days": rule.retention_days,
+    })).await?;
+
+    Ok(Json(rule))
+}
+
+/// DELETE /api/admin/payload-captures/rules/:id
+/// Stop capturing; stored captures are kept until they expire
+#[utoipa::path(
+    delete,
+    path = "/api/admin/payload-captures/rules/{id}",
+    tag = "payload_captures",
+    summary = "Stop capturing; stored captures are kept until they expire",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = PayloadCaptureRule),
+    )
+)]
+pub async fn revoke_capture_rule(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(rule_id): Path<Uuid>,
+) -> Result<Json<PayloadCaptureRule>> {
+    require_superadmin!(claims);
+    let service = PayloadCaptureService::new(config.database_pool.clone());
+    let rule = service.revoke_rule(rule_id, claims.user_id).await?;
+
+    audit(&config, &claims, "admin_payload_capture_revoked", "revoke_capture_rule", rule.id, serde_json::json!({
+        "reason": rule.reason,
+    })).await?;
+
+    Ok(Json(rule))
+}
+
+/// GET /api/admin/payload-captures?rule_id=&user_id=&limit=&offset=
+/// Captures without their bodies
+#[utoipa::path(
+    get,
+    path = "/api/admin/payload-captures",
+    tag = "payload_captures",
+    summary = "Captures without their bodies",
+    params(
+        PayloadCaptureQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<PayloadCaptureSummary>),
+    )
+)]
+pub async fn list_captures(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<PayloadCaptureQuery>,
+) -> Result<Json<Vec<PayloadCaptureSummary>>> {
+    require_superadmin!(claims);
+    let service = PayloadCaptureService::new(config.database_pool.clone());
+    Ok(Json(service.list_captures(&query).await?))
+}
+
+/// GET /api/admin/payload-captures/:id
+/// One capture with its bodies; every view is audited
+#[utoipa::path(
+    get,
+    path = "/api/admin/payload-captures/{id}",
+    tag = "payload_captures",
+    summary = "One capture with its bodies; every view is audited",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = PayloadCapture),
+    )
+)]
+pub async fn get_capture(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(capture_id): Path<Uuid>,
+) -> Result<Json<PayloadCapture>> {
+    require_superadmin!(claims);
+    let service = PayloadCaptureService::new(config.database_pool.clone());
+    let capture = service.get_capture(capture_id).await?;
+
+    audit(&config, &claims, "admin_payload_capture_viewed", "view_capture", capture.id, serde_json::json!({
+        "rule_id": capture.rule_id,
+        "user_id": capture.user_id,
+        "route": capture.route,
+    })).await?;
+
+    Ok(Json(capture))
+}
+
+async fn audit(
+    config: &AppConfig,
+    claims: &Claims,
+    event_type: &str,
+    action: &str,
+    resource_id: Uuid,
+    event_data: serde_json::Value,
+) -> Result<()> {
+    ComprehensiveAuditService::new(config.database_pool.clone()).log(AuditLogEntry {
+        event_type: event_type.to_string(),
+        event_category: EventCategory::Admin,
+        severity: Severity::Warning,
+        actor_user_id: Some(claims.user_id),
+        actor_type: "user".to_string(),
+        resource_type: Some("payload_capture".to_string()),
+        resource_id: Some(resource_id.to_string()),
+        action: action.to_string(),
+        action_result: ActionResult::Success,
+        event_data,
+        compliance_tags: vec!["admin".to_string(), "investigation".to_string(), "pii".to_string()],
+        ..Default::default()
+    }).await?;
+
+    Ok(())
+}
@@ -1,16 +1,31 @@
 use axum::{
     extract::{Path, Query, State},
+    http::{header, HeaderValue},
+    response::{IntoResponse, Response},
     Json,
     Extension,
 };
 use validator::Validate;
 use crate::{
-    models::{pharmaceutical::{CreatePharmaceuticalRequest, SearchPharmaceuticalRequest}},
-    services::PharmaService,
-    middleware::{error_handling::Result, Claims},
+    models::{
+        pharmaceutical::{CreatePharmaceuticalRequest, SearchPharmaceuticalRequest},
+        ingredient::{PharmaceuticalIngredientsResponse, SetIngredientsRequest},
+        summary_aggregate::Summarized,
+    },
+    services::{expand_catalog_entries, PharmaService},
+    middleware::{error_handling::Result, Claims, ExpandQuery},
     config::AppConfig,
 };
 
+#[utoipa::path(
+    post,
+    path = "/api/pharmaceuticals",
+    tag = "pharmaceutical",
+    request_body = CreatePharmaceuticalRequest,
+    responses(
+        (status = 200, description = "Success", body = crate::models::pharmaceutical::PharmaceuticalResponse),
+    )
+)]
 pub async fn create_pharmaceutical(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -31,18 +46,47 @@ pub async fn create_pharmaceutical(
     Ok(Json(pharma))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/pharmaceuticals/{id}",
+    tag = "pharmaceutical",
+    params(
+        ("id" = uuid::Uuid, Path),
+        ExpandQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = crate::models::pharmaceutical::PharmaceuticalResponse),
+    )
+)]
 pub async fn get_pharmaceutical(
     State(config): State<AppConfig>,
     Path(id): Path<uuid::Uuid>,
+    Query(expand): Query<ExpandQuery>,
 ) -> Result<Json<crate::models::pharmaceutical::PharmaceuticalResponse>> {
+    let expansions = expand.expansions(&["catalog_entry"])?;
     let pharma_service = PharmaService::new(
         crate::repositories::PharmaceuticalRepository::new(config.database_pool.clone())
     );
 
-    let pharma = pharma_service.get_pharmaceutical(id).await?;
+    let mut pharma = pharma_service.get_pharmaceutical(id).await?;
+    if expansions.contains("catalog_entry") {
+        let catalog = crate::repositories::OpenFdaRepository::new(config.database_pool.clone());
+        expand_catalog_entries(&catalog, vec![&mut pharma]).await?;
+    }
     Ok(Json(pharma))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/pharmaceuticals/search",
+    tag = "pharmaceutical",
+    params(
+        SearchPharmaceuticalRequest,
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<crate::models::pharmaceutical::PharmaceuticalResponse>),
+    )
+)]
 pub async fn search_pharmaceuticals(
     State(config): State<AppConfig>,
     Query(request): Query<SearchPharmaceuticalRequest>,
@@ -55,24 +99,108 @@ pub async fn search_pharmaceuticals(
     Ok(Json(results))
 }
 
+/// Manufacturer names; Last-Modified is when the list was last refreshed
+#[utoipa::path(
+    get,
+    path = "/api/pharmaceuticals/manufacturers",
+    tag = "pharmaceutical",
+    operation_id = "pharmaceutical_get_manufacturers",
+    responses(
+        (status = 200, description = "Manufacturer names", body = Vec<String>),
+    )
+)]
 pub async fn get_manufacturers(
     State(config): State<AppConfig>,
-) -> Result<Json<Vec<String>>> {
+) -> Result<Response> {
     let pharma_service = PharmaService::new(
         crate::repositories::PharmaceuticalRepository::new(config.database_pool.clone())
     );
 
     let manufacturers = pharma_service.get_manufacturers().await?;
-    Ok(Json(manufacturers))
+    Ok(summary_response(manufacturers))
 }
 
+/// Category names; Last-Modified is when the list was last refreshed
+#[utoipa::path(
+    get,
+    path = "/api/pharmaceuticals/categories",
+    tag = "pharmaceutical",
+    responses(
+        (status = 200, description = "Category names", body = Vec<String>),
+    )
+)]
 pub async fn get_categories(
     State(config): State<AppConfig>,
-) -> Result<Json<Vec<String>>> {
+) -> Result<Response> {
     let pharma_service = PharmaService::new(
         crate::repositories::PharmaceuticalRepository::new(config.database_pool.clone())
     );
 
     let categories = pharma_service.get_categories().await?;
-    Ok(Json(categories))
-}
\ No newline at end of file
+    Ok(summary_response(categories))
+}
+
+fn summary_response(summary: Summarized<Vec<String>>) -> Response {
+    let last_modified = summary.last_modified();
+    let mut response = Json(summary.value).into_response();
+    if let Some(value) = last_modified.and_then(|value| HeaderValue::from_str(&value).ok()) {
+        response.headers_mut().insert(header::LAST_MODIFIED, value);
+    }
+    response
+}
+
+/// Active and inactive ingredients of a product, its allergens, and whether the
+/// excipients have been declared
+#[utoipa::path(
+    get,
+    path = "/api/pharmaceuticals/{id}/ingredients",
+    tag = "pharmaceutical",
+    params(
+        ("id" = uuid::Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = PharmaceuticalIngredientsResponse),
+    )
+)]
+pub async fn get_ingredients(
+    State(config): State<AppConfig>,
+    Path(id): Path<uuid::Uuid>,
+) -> Result<Json<PharmaceuticalIngredientsResponse>> {
+    let pharma_service = PharmaService::new(
+        crate::repositories::PharmaceuticalRepository::new(config.database_pool.clone())
+    );
+
+    let ingredients = pharma_service.get_ingredients(id).await?;
+    Ok(Json(ingredients))
+}
+
+/// Replace a product's ingredients (admins only, since catalog entries are shared and
+/// `free_of` filters depend on them); the excipients count as declared only when
+/// `inactive_ingredients` or `inactive_ingredients_text` is given
+#[utoipa::path(
+    put,
+    path = "/api/pharmaceuticals/{id}/ingredients",
+    tag = "pharmaceutical",
+    params(
+        ("id" = uuid::Uuid, Path),
+    ),
+    request_body = SetIngredientsRequest,
+    responses(
+        (status = 200, description = "Success", body = PharmaceuticalIngredientsResponse),
+    )
+)]
+pub async fn set_ingredients(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<uuid::Uuid>,
+    Json(request): Json<SetIngredientsRequest>,
+) -> Result<Json<PharmaceuticalIngredientsResponse>> {
+    crate::require_admin!(claims);
+
+    let pharma_service = PharmaService::new(
+        crate::repositories::PharmaceuticalRepository::new(config.database_pool.clone())
+    );
+
+    let ingredients = pharma_service.set_ingredients(id, request).await?;
+    Ok(Json(ingredients))
+}
@@ -0,0 +1,133 @@
+/// REST API handlers for AI listing price suggestions
+
+use axum::{
+    extract::{Path, Query, State},
+    Extension,
+    Json,
+};
+use uuid::Uuid;
+use crate::{
+    config::AppConfig,
+    middleware::{error_handling::{Result, AppError}, Claims},
+    models::pricing_suggestion::*,
+    services::{AiQuotaService, PricingSuggestionService},
+};
+
+fn pricing_service(config: &AppConfig) -> Result<PricingSuggestionService> {
+    let claude_api_key = std::env::var("ANTHROPIC_API_KEY")
+        .map_err(|_| AppError::Internal(anyhow::anyhow!("ANTHROPIC_API_KEY not configured")))?;
+
+    Ok(PricingSuggestionService::new(config.database_pool.clone(), claude_api_key))
+}
+
+/// POST /api/ai-pricing/suggestions
+/// Suggest a unit price for listing an NDC with the given expiry window and quantity
+#[utoipa::path(
+    post,
+    path = "/api/ai-pricing/suggestions",
+    tag = "pricing_suggestions",
+    operation_id = "pricing_suggestions_generate_suggestion",
+    summary = "Suggest a unit price for listing an NDC with the given expiry window and quantity",
+    request_body = PricingSuggestionRequest,
+    responses(
+        (status = 200, description = "Success", body = PricingSuggestionResponse),
+    )
+)]
+pub async fn generate_suggestion(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<PricingSuggestionRequest>,
+) -> Result<Json<PricingSuggestionResponse>> {
+    tracing::info!(
+        "Pricing suggestion requested: ndc={}, quantity={}, user={}",
+        request.ndc_code,
+        request.quantity,
+        claims.user_id
+    );
+
+    let service = pricing_service(&config)?;
+    let suggestion = service.suggest_price(claims.user_id, request).await?;
+
+    Ok(Json(suggestion.into()))
+}
+
+/// GET /api/ai-pricing/suggestions?ndc_code=&limit=
+/// User's past suggestions, newest first
+#[utoipa::path(
+    get,
+    path = "/api/ai-pricing/suggestions",
+    tag = "pricing_suggestions",
+    summary = "User's past suggestions, newest first",
+    params(
+        PricingHistoryQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<PricingSuggestionResponse>),
+    )
+)]
+pub async fn list_suggestions(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<PricingHistoryQuery>,
+) -> Result<Json<Vec<PricingSuggestionResponse>>> {
+    let service = pricing_service(&config)?;
+    let suggestions = service.get_history(claims.user_id, &query).await?;
+
+    Ok(Json(suggestions.into_iter().map(Into::into).collect()))
+}
+
+/// GET /api/ai-pricing/suggestions/:id
+/// A suggestion with the market snapshot it was based on
+#[utoipa::path(
+    get,
+    path = "/api/ai-pricing/suggestions/{id}",
+    tag = "pricing_suggestions",
+    operation_id = "pricing_suggestions_get_suggestion",
+    summary = "A suggestion with the market snapshot it was based on",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = PricingSuggestionResponse),
+    )
+)]
+pub async fn get_suggestion(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(suggestion_id): Path<Uuid>,
+) -> Result<Json<PricingSuggestionResponse>> {
+    let service = pricing_service(&config)?;
+    let suggestion = service.get_suggestion(suggestion_id, claims.user_id).await?;
+
+    Ok(Json(suggestion.into()))
+}
+
+/// GET /api/ai-pricing/quota
+/// Get user's pricing suggestion quota status
+#[utoipa::path(
+    get,
+    path = "/api/ai-pricing/quota",
+    tag = "pricing_suggestions",
+    operation_id = "pricing_suggestions_get_quota",
+    summary = "Get user's pricing suggestion quota status",
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
+pub async fn get_quota(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<serde_json::Value>> {
+    let service = pricing_service(&config)?;
+    let (limit, used, remaining) = service.get_quota_status(claims.user_id).await?;
+    let usage = AiQuotaService::new(config.database_pool.clone())
+        .get_usage(claims.user_id)
+        .await?;
+
+    Ok(Json(serde_json::json!({
+        "suggestion_limit": limit,
+        "suggestions_used": used,
+        "suggestions_remaining": remaining,
+        "usage": usage
+    })))
+}
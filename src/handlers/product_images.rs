@@ -0,0 +1,438 @@
+/// Product Image REST API Handlers
+///
+/// Image upload, moderation and CDN-friendly delivery for catalog pharmaceuticals.
+
+use axum::{
+    extract::{Multipart, Path, Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use uuid::Uuid;
+use crate::{
+    config::AppConfig,
+    middleware::{error_handling::{AppError, Result}, Claims},
+    models::product_image::*,
+    services::ProductImageService,
+};
+
+/// Approved images are immutable (a new upload always gets a new id), so caches may keep them for a year
+const APPROVED_CACHE_CONTROL: &str = "public, max-age=31536000, immutable";
+
+/// Unmoderated images must never end up in shared caches
+const PRIVATE_CACHE_CONTROL: &str = "private, no-store";
+
+/// POST /api/pharmaceuticals/:id/images
+/// Upload an image (multipart fields: `file`, optional `alt_text`, optional `is_primary`)
+#[utoipa::path(
+    post,
+    path = "/api/pharmaceuticals/{id}/images",
+    tag = "product_images",
+    summary = "Upload an image (multipart fields: `file`, optional `alt_text`, optional `is_primary`)",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    request_body(content = crate::openapi::ImageUploadForm, content_type = "multipart/form-data"),
+    responses(
+        (status = 200, description = "Success", body = PharmaceuticalImageResponse),
+    )
+)]
+pub async fn upload_image(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(pharmaceutical_id): Path<Uuid>,
+    mut multipart: Multipart,
+) -> Result<Json<PharmaceuticalImageResponse>> {
+    if !claims.is_admin() && !claims.is_verified {
+        return Err(AppError::Forbidden("Only verified sellers can upload product images".to_string()));
+    }
+
+    let mut file_data: Option<Vec<u8>> = None;
+    let mut filename: Option<String> = None;
+    let mut alt_text: Option<String> = None;
+    let mut is_primary = false;
+
+    while let Some(field) = multipart.next_field().await.map_err(|e| {
+        AppError::InvalidInput(format!("Invalid multipart data: {}", e))
+    })? {
+        let field_name = field.name().unwrap_or("").to_string();
+
+        match field_name.as_str() {
+            "file" => {
+                filename = field.file_name().map(|s| s.to_string());
+                file_data = Some(field.bytes().await.map_err(|e| {
+                    AppError::InvalidInput(format!("Failed to read file: {}", e))
+                })?.to_vec());
+            }
+            "alt_text" => {
+                let text = field.text().await.map_err(|e| {
+                    AppError::InvalidInput(format!("Invalid alt_text: {}", e))
+                })?;
+                if text.chars().count() > 255 {
+                    return Err(AppError::InvalidInput("alt_text must be at most 255 characters".to_string()));
+                }
+                alt_text = Some(text).filter(|t| !t.trim().is_empty());
+            }
+            "is_primary" => {
+                let value = field.text().await.unwrap_or_default();
+                is_primary = matches!(value.trim(), "true" | "1");
+            }
+            _ => {}
+        }
+    }
+
+    let file_data = file_data.ok_or_else(|| AppError::InvalidInput("No file provided".to_string()))?;
+    let filename = filename.unwrap_or_else(|| "image".to_string());
+
+    let service = ProductImageService::new(config.database_pool.clone(), &config.file_storage_path)?;
+    let image = service
+        .upload_image(
+            pharmaceutical_id,
+            claims.user_id,
+            claims.is_admin(),
+            &filename,
+            &file_data,
+            alt_text,
+            is_primary,
+        )
+        .await?;
+
+    Ok(Json(image.into()))
+}
+
+/// GET /api/pharmaceuticals/:id/images
+/// List images for a pharmaceutical
+#[utoipa::path(
+    get,
+    path = "/api/pharmaceuticals/{id}/images",
+    tag = "product_images",
+    summary = "List images for a pharmaceutical",
+    params(
+        ("id" = Uuid, Path),
+        ListImagesQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<PharmaceuticalImageResponse>),
+    )
+)]
+pub async fn list_images(
+    State(config): State<AppConfig>,
+    claims: Option<Extension<Claims>>,
+    Path(pharmaceutical_id): Path<Uuid>,
+    Query(query): Query<ListImagesQuery>,
+) -> Result<Json<Vec<PharmaceuticalImageResponse>>> {
+    let service = ProductImageService::new(config.database_pool.clone(), &config.file_storage_path)?;
+
+    let viewer_id = claims.as_ref().map(|c| c.user_id);
+    let viewer_is_admin = claims.as_ref().map(|c| c.is_admin()).unwrap_or(false);
+
+    let images = service
+        .list_images(pharmaceutical_id, viewer_id, viewer_is_admin, query.include_unmoderated)
+        .await?;
+
+    Ok(Json(images.into_iter().map(Into::into).collect()))
+}
+
+/// GET /api/pharmaceuticals/:id/images/:image_id/file
+/// Serve the original image
+#[utoipa::path(
+    get,
+    path = "/api/pharmaceuticals/{id}/images/{image_id}/file",
+    tag = "product_images",
+    summary = "Serve the original image",
+    params(
+        ("id" = Uuid, Path),
+        ("image_id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "The image", body = crate::openapi::FileDownload, content_type = "image/*"),
+    )
+)]
+pub async fn get_image_file(
+    State(config): State<AppConfig>,
+    claims: Option<Extension<Claims>>,
+    Path((pharmaceutical_id, image_id)): Path<(Uuid, Uuid)>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    serve_image(config, claims, pharmaceutical_id, image_id, headers, false).await
+}
+
+/// GET /api/pharmaceuticals/:id/images/:image_id/thumbnail
+/// Serve the generated thumbnail
+#[utoipa::path(
+    get,
+    path = "/api/pharmaceuticals/{id}/images/{image_id}/thumbnail",
+    tag = "product_images",
+    summary = "Serve the generated thumbnail",
+    params(
+        ("id" = Uuid, Path),
+        ("image_id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "The thumbnail", body = crate::openapi::FileDownload, content_type = "image/*"),
+    )
+)]
+pub async fn get_image_thumbnail(
+    State(config): State<AppConfig>,
+    claims: Option<Extension<Claims>>,
+    Path((pharmaceutical_id, image_id)): Path<(Uuid, Uuid)>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    serve_image(config, claims, pharmaceutical_id, image_id, headers, true).await
+}
+
+/// PUT /api/pharmaceuticals/:id/images/:image_id/moderate
+/// Approve or reject an image (admin only)
+#[utoipa::path(
+    put,
+    path = "/api/pharmaceuticals/{id}/images/{image_id}/moderate",
+    tag = "product_images",
+    summary = "Approve or reject an image (admin only)",
+    params(
+        ("id" = Uuid, Path),
+        ("image_id" = Uuid, Path),
+    ),
+    request_body = ModerateImageRequest,
+    responses(
+        (status = 200, description = "Success", body = PharmaceuticalImageResponse),
+    )
+)]
+pub async fn moderate_image(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path((pharmaceutical_id, image_id)): Path<(Uuid, Uuid)>,
+    Json(request): Json<ModerateImageRequest>,
+) -> Result<Json<PharmaceuticalImageResponse>> {
+    crate::require_admin!(claims);
+
+    let service = ProductImageService::new(config.database_pool.clone(), &config.file_storage_path)?;
+    let image = service
+        .moderate_image(pharmaceutical_id, image_id, claims.user_id, request)
+        .await?;
+
+    Ok(Json(image.into()))
+}
+
+/// PUT /api/pharmaceuticals/:id/images/:image_id/primary
+/// Make an approved image the primary image (admin only)
+#[utoipa::path(
+    put,
+    path = "/api/pharmaceuticals/{id}/images/{image_id}/primary",
+    tag = "product_images",
+    summary = "Make an approved image the primary image (admin only)",
+    params(
+        ("id" = Uuid, Path),
+        ("image_id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = PharmaceuticalImageResponse),
+    )
+)]
+pub async fn set_primary_image(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path((pharmaceutical_id, image_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<PharmaceuticalImageResponse>> {
+    crate::require_admin!(claims);
+
+    let service = ProductImageService::new(config.database_pool.clone(), &config.file_storage_path)?;
+    let image = service.set_primary(pharmaceutical_id, image_id).await?;
+
+    Ok(Json(image.into()))
+}
+
+/// DELETE /api/pharmaceuticals/:id/images/:image_id
+/// Delete an image (admin, or the seller who uploaded it)
+#[utoipa::path(
+    delete,
+    path = "/api/pharmaceuticals/{id}/images/{image_id}",
+    tag = "product_images",
+    summary = "Delete an image (admin, or the seller who uploaded it)",
+    params(
+        ("id" = Uuid, Path),
+        ("image_id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
+pub async fn delete_image(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path((pharmaceutical_id, image_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<serde_json::Value>> {
+    let service = ProductImageService::new(config.database_pool.clone(), &config.file_storage_path)?;
+    service
+        .delete_image(pharmaceutical_id, image_id, claims.user_id, claims.is_admin())
+        .await?;
+
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "message": "Image deleted"
+    })))
+}
+
+/// GET /api/pharmaceuticals/images/moderation-queue
+/// Pending images awaiting moderation (admin only)
+#[utoipa::path(
+    get,
+    path = "/api/pharmaceuticals/images/moderation-queue",
+    tag = "product_images",
+    summary = "Pending images awaiting moderation (admin only)",
+    params(
+        ModerationQueueQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<PharmaceuticalImageResponse>),
+    )
+)]
+pub async fn get_moderation_queue(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<ModerationQueueQuery>,
+) -> Result<Json<Vec<PharmaceuticalImageResponse>>> {
+    crate::require_admin!(claims);
+
+    let service = ProductImageService::new(config.database_pool.clone(), &config.file_storage_path)?;
+    let images = service
+        .get_moderation_queue(query.limit.unwrap_or(50), query.offset.unwrap_or(0))
+        .await?;
+
+    Ok(Json(images.into_iter().map(Into::into).collect()))
+}
+
+async fn serve_image(
+    config: AppConfig,
+    claims: Option<Extension<Claims>>,
+    pharmaceutical_id: Uuid,
+    image_id: Uuid,
+    headers: HeaderMap,
+    thumbnail: bool,
+) -> Result<Response> {
+    let service = ProductImageService::new(config.database_pool.clone(), &config.file_storage_path)?;
+    let image = service.get_image(pharmaceutical_id, image_id).await?;
+
+    let approved = image.moderation_status == ModerationStatus::Approved.as_str();
+    if !can_view_image(&image, claims.as_deref()) {
+        // Same response as a missing image so unmoderated uploads can't be probed
+        return Err(AppError::NotFound("Image not found".to_string()));
+    }
+
+    let etag = if thumbnail {
+        format!("\"{}-thumb\"", image.file_hash)
+    } else {
+        format!("\"{}\"", image.file_hash)
+    };
+    let cache_control = if approved { APPROVED_CACHE_CONTROL } else { PRIVATE_CACHE_CONTROL };
+
+    let not_modified = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.split(',').any(|tag| tag.trim() == etag || tag.trim() == "*"))
+        .unwrap_or(false);
+
+    let etag_value = HeaderValue::from_str(&etag)
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Invalid ETag: {}", e)))?;
+
+    if not_modified {
+        return Ok((
+            StatusCode::NOT_MODIFIED,
+            [
+                (header::ETAG, etag_value),
+                (header::CACHE_CONTROL, HeaderValue::from_static(cache_control)),
+            ],
+        )
+            .into_response());
+    }
+
+    let data = service.read_image_file(&image, thumbnail)?;
+    let content_type = if thumbnail {
+        thumbnail_content_type(&image.thumbnail_path)
+    } else {
+        image.content_type.as_str()
+    };
+
+    Ok((
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, HeaderValue::from_str(content_type).unwrap_or(HeaderValue::from_static("application/octet-stream"))),
+            (header::ETAG, etag_value),
+            (header::CACHE_CONTROL, HeaderValue::from_static(cache_control)),
+        ],
+        data,
+    )
+        .into_response())
+}
+
+fn thumbnail_content_type(path: &str) -> &'static str {
+    if path.ends_with(".jpg") {
+        "image/jpeg"
+    } else {
+        "image/png"
+    }
+}
+
+/// Approved images are public; unmoderated and rejected ones are visible to admins and
+/// the uploader (the routes carry optional auth, so a signed-in viewer has claims)
+fn can_view_image(image: &PharmaceuticalImage, claims: Option<&Claims>) -> bool {
+    image.moderation_status == ModerationStatus::Approved.as_str()
+        || claims.is_some_and(|c| c.is_admin() || image.uploaded_by == Some(c.user_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::user::UserRole;
+
+    fn image(uploaded_by: Uuid, moderation_status: ModerationStatus) -> PharmaceuticalImage {
+        PharmaceuticalImage {
+            id: Uuid::new_v4(),
+            pharmaceutical_id: Uuid::new_v4(),
+            uploaded_by: Some(uploaded_by),
+            file_path: "images/a.png".to_string(),
+            thumbnail_path: "images/a_thumb.png".to_string(),
+            original_filename: None,
+            content_type: "image/png".to_string(),
+            file_size_bytes: 1024,
+            file_hash: "ab".repeat(32),
+            width: 100,
+            height: 100,
+            alt_text: None,
+            is_primary: false,
+            moderation_status: moderation_status.as_str().to_string(),
+            moderated_by: None,
+            moderated_at: None,
+            rejection_reason: None,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    fn claims(user_id: Uuid, role: UserRole) -> Claims {
+        Claims {
+            sub: user_id.to_string(),
+            user_id,
+            email: "seller@example.com".to_string(),
+            company_name: "Example Pharma".to_string(),
+            is_verified: true,
+            role,
+            exp: usize::MAX,
+            iat: 0,
+            jti: Uuid::new_v4().to_string(),
+            audience: None,
+        }
+    }
+
+    #[test]
+    fn test_owner_sees_unmoderated_image() {
+        let owner = Uuid::new_v4();
+        let pending = image(owner, ModerationStatus::Pending);
+
+        assert!(can_view_image(&pending, Some(&claims(owner, UserRole::User))));
+        assert!(can_view_image(&pending, Some(&claims(Uuid::new_v4(), UserRole::Admin))));
+        assert!(!can_view_image(&pending, Some(&claims(Uuid::new_v4(), UserRole::User))));
+        assert!(!can_view_image(&pending, None));
+
+        assert!(can_view_image(&image(owner, ModerationStatus::Approved), None));
+    }
+}
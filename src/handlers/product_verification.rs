@@ -0,0 +1,89 @@
+/// Product Verification REST API Handlers
+///
+/// VRS-style lookup of a product identifier (GTIN, serial, lot, expiry) against
+/// serialized inventory, serial number registration for inventory lots, and the
+/// requester's lookup log.
+
+use axum::{
+    extract::{Query, State},
+    Extension, Json,
+};
+use validator::Validate;
+use crate::{
+    config::AppConfig,
+    middleware::{error_handling::{AppError, Result}, Claims},
+    models::product_verification::*,
+    services::ProductVerificationService,
+};
+
+/// POST /api/verification/lookup
+/// Verify a product identifier: verified, not_found or recalled
+#[utoipa::path(
+    post,
+    path = "/api/verification/lookup",
+    tag = "product_verification",
+    summary = "Verify a product identifier: verified, not_found or recalled",
+    request_body = VerificationLookupRequest,
+    responses(
+        (status = 200, description = "Success", body = VerificationLookupResponse),
+    )
+)]
+pub async fn verification_lookup(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<VerificationLookupRequest>,
+) -> Result<Json<VerificationLookupResponse>> {
+    request.validate().map_err(AppError::Validation)?;
+
+    let service = ProductVerificationService::new(config.database_pool.clone());
+    Ok(Json(service.lookup(claims.user_id, request).await?))
+}
+
+/// POST /api/verification/serials
+/// Register serial numbers (one GTIN) for one of the user's inventory lots
+#[utoipa::path(
+    post,
+    path = "/api/verification/serials",
+    tag = "product_verification",
+    summary = "Register serial numbers (one GTIN) for one of the user's inventory lots",
+    request_body = RegisterSerialNumbersRequest,
+    responses(
+        (status = 200, description = "Success", body = RegisterSerialNumbersResponse),
+    )
+)]
+pub async fn register_serial_numbers(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<RegisterSerialNumbersRequest>,
+) -> Result<Json<RegisterSerialNumbersResponse>> {
+    request.validate().map_err(AppError::Validation)?;
+
+    let service = ProductVerificationService::new(config.database_pool.clone());
+    Ok(Json(service.register_serials(claims.user_id, request).await?))
+}
+
+/// GET /api/verification/requests
+/// The user's verification lookups, newest first
+#[utoipa::path(
+    get,
+    path = "/api/verification/requests",
+    tag = "product_verification",
+    summary = "The user's verification lookups, newest first",
+    params(
+        VerificationHistoryQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<ProductVerificationRecord>),
+    )
+)]
+pub async fn list_verification_requests(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<VerificationHistoryQuery>,
+) -> Result<Json<Vec<ProductVerificationRecord>>> {
+    let limit = query.limit.unwrap_or(50).clamp(1, 200);
+    let offset = query.offset.unwrap_or(0).max(0);
+
+    let service = ProductVerificationService::new(config.database_pool.clone());
+    Ok(Json(service.history(claims.user_id, limit, offset).await?))
+}
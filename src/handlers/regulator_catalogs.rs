@@ -0,0 +1,231 @@
+/// Regulator Catalog REST API Handlers
+///
+/// Search and sync the UK MHRA, Health Canada and Swissmedic product catalogs.
+/// `:source` is one of `mhra`, `health_canada` (or `health-canada`) and `swissmedic`.
+/// `/api/catalogs/search` searches these together with OpenFDA and EMA.
+
+use axum::{
+    extract::{Path, Query, State},
+    Extension, Json,
+};
+use serde::Deserialize;
+use utoipa::IntoParams;
+use crate::{
+    config::{AppConfig, ReadConsistency},
+    handlers::openfda::TriggerSyncResponse,
+    middleware::{error_handling::{AppError, Result}, Claims},
+    models::{federated_search::{FederatedSearchRequest, FederatedSearchResponse}, regulator_catalog::*},
+    services::{regulator_catalogs::RegulatorCatalogService, FederatedSearchService},
+};
+
+fn parse_source(source: &str) -> Result<RegulatorSource> {
+    RegulatorSource::parse(source).ok_or_else(|| {
+        AppError::NotFound(format!(
+            "Unknown catalog source '{}'. Available: mhra, health_canada, swissmedic",
+            source
+        ))
+    })
+}
+
+/// GET /api/catalogs
+/// Available regulator catalogs with entry counts and last sync
+#[utoipa::path(
+    get,
+    path = "/api/catalogs",
+    tag = "regulator_catalogs",
+    operation_id = "regulator_catalogs_list_sources",
+    summary = "Available regulator catalogs with entry counts and last sync",
+    responses(
+        (status = 200, description = "Success", body = Vec<RegulatorCatalogStats>),
+    )
+)]
+pub async fn list_sources(
+    State(config): State<AppConfig>,
+) -> Result<Json<Vec<RegulatorCatalogStats>>> {
+    let service = RegulatorCatalogService::new(config.database_pool.clone());
+
+    let mut sources = Vec::with_capacity(RegulatorSource::ALL.len());
+    for source in RegulatorSource::ALL {
+        sources.push(service.get_stats(source).await?);
+    }
+
+    Ok(Json(sources))
+}
+
+/// GET /api/catalogs/search
+/// Search OpenFDA, EMA and the regulator catalogs at once (`sources` narrows the set).
+/// Results carry their source and equivalence key; `groups` lists products of the same
+/// substance found in several catalogs.
+#[utoipa::path(
+    get,
+    path = "/api/catalogs/search",
+    tag = "regulator_catalogs",
+    summary = "Search OpenFDA, EMA and the regulator catalogs at once (`sources` narrows the set)",
+    params(
+        FederatedSearchRequest,
+    ),
+    responses(
+        (status = 200, description = "Success", body = FederatedSearchResponse),
+    )
+)]
+pub async fn federated_search(
+    State(config): State<AppConfig>,
+    Query(request): Query<FederatedSearchRequest>,
+) -> Result<Json<FederatedSearchResponse>> {
+    let service = FederatedSearchService::new(config.read_pool(ReadConsistency::Relaxed));
+    let response = service.search(&request).await?;
+
+    Ok(Json(response))
+}
+
+/// GET /api/catalogs/:source/search
+/// Search a catalog by product name, ingredient, ATC code or authorization status
+#[utoipa::path(
+    get,
+    path = "/api/catalogs/{source}/search",
+    tag = "regulator_catalogs",
+    operation_id = "regulator_catalogs_search_catalog",
+    summary = "Search a catalog by product name, ingredient, ATC code or authorization status",
+    params(
+        ("source" = String, Path),
+        RegulatorCatalogSearchRequest,
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<RegulatorCatalogEntry>),
+    )
+)]
+pub async fn search_catalog(
+    State(config): State<AppConfig>,
+    Path(source): Path<String>,
+    Query(request): Query<RegulatorCatalogSearchRequest>,
+) -> Result<Json<Vec<RegulatorCatalogEntry>>> {
+    let source = parse_source(&source)?;
+    let service = RegulatorCatalogService::new(config.read_pool(ReadConsistency::Relaxed));
+    let results = service.search(source, &request).await?;
+
+    Ok(Json(results))
+}
+
+/// GET /api/catalogs/:source/products/:source_id
+/// A product by its source identifier (PL number, DIN or Swissmedic authorization number)
+#[utoipa::path(
+    get,
+    path = "/api/catalogs/{source}/products/{source_id}",
+    tag = "regulator_catalogs",
+    summary = "A product by its source identifier (PL number, DIN or Swissmedic authorization number)",
+    params(
+        ("source" = String, Path),
+        ("source_id" = String, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = RegulatorCatalogEntry),
+    )
+)]
+pub async fn get_product(
+    State(config): State<AppConfig>,
+    Path((source, source_id)): Path<(String, String)>,
+) -> Result<Json<RegulatorCatalogEntry>> {
+    let source = parse_source(&source)?;
+    let service = RegulatorCatalogService::new(config.database_pool.clone());
+    let entry = service.get_entry(source, &source_id).await?;
+
+    Ok(Json(entry))
+}
+
+/// GET /api/catalogs/:source/stats
+#[utoipa::path(
+    get,
+    path = "/api/catalogs/{source}/stats",
+    tag = "regulator_catalogs",
+    operation_id = "regulator_catalogs_get_stats",
+    params(
+        ("source" = String, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = RegulatorCatalogStats),
+    )
+)]
+pub async fn get_stats(
+    State(config): State<AppConfig>,
+    Path(source): Path<String>,
+) -> Result<Json<RegulatorCatalogStats>> {
+    let source = parse_source(&source)?;
+    let service = RegulatorCatalogService::new(config.read_pool(ReadConsistency::Relaxed));
+    let stats = service.get_stats(source).await?;
+
+    Ok(Json(stats))
+}
+
+/// POST /api/catalogs/:source/sync
+/// Trigger a sync (admin only)
+#[utoipa::path(
+    post,
+    path = "/api/catalogs/{source}/sync",
+    tag = "regulator_catalogs",
+    operation_id = "regulator_catalogs_trigger_sync",
+    summary = "Trigger a sync (admin only)",
+    params(
+        ("source" = String, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = TriggerSyncResponse),
+    )
+)]
+pub async fn trigger_sync(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(source): Path<String>,
+) -> Result<Json<TriggerSyncResponse>> {
+    crate::require_admin!(claims);
+
+    let source = parse_source(&source)?;
+    let service = RegulatorCatalogService::new(config.database_pool.clone());
+    let sync_id = service.start_background_sync(source, "manual").await?;
+
+    Ok(Json(TriggerSyncResponse {
+        sync_id,
+        message: format!(
+            "{} sync started in background. Use /api/catalogs/{}/sync/logs to check progress.",
+            source.display_name(),
+            source.as_str()
+        ),
+    }))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SyncLogsQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// GET /api/catalogs/:source/sync/logs
+/// Sync history for a catalog (admin only)
+#[utoipa::path(
+    get,
+    path = "/api/catalogs/{source}/sync/logs",
+    tag = "regulator_catalogs",
+    operation_id = "regulator_catalogs_get_sync_logs",
+    summary = "Sync history for a catalog (admin only)",
+    params(
+        ("source" = String, Path),
+        SyncLogsQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<CatalogSyncLog>),
+    )
+)]
+pub async fn get_sync_logs(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(source): Path<String>,
+    Query(query): Query<SyncLogsQuery>,
+) -> Result<Json<Vec<CatalogSyncLog>>> {
+    crate::require_admin!(claims);
+
+    let source = parse_source(&source)?;
+    let service = RegulatorCatalogService::new(config.database_pool.clone());
+    let logs = service.get_sync_logs(source, query.limit, query.offset).await?;
+
+    Ok(Json(logs))
+}
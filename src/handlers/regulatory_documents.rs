@@ -8,19 +8,26 @@ use axum::{
     Extension, Json,
 };
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 
 use crate::{
     config::AppConfig,
-    middleware::{error_handling::Result, Claims},
-    services::{GenerateDocumentRequest, GeneratedDocument, RegulatoryDocumentGenerator},
+    middleware::{error_handling::{AppError, Result}, Claims},
+    models::{document_version::*, electronic_signature::*},
+    services::{
+        load_document_citations, DocumentVersionService, ElectronicSignatureService, GenerateDocumentRequest,
+        GeneratedDocument, RegulatoryDocumentGenerator,
+    },
 };
+use validator::Validate;
 
 // ============================================================================
 // REQUEST/RESPONSE MODELS
 // ============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct ListDocumentsQuery {
     #[serde(default)]
     pub document_type: Option<String>, // 'CoA', 'GDP', 'GMP'
@@ -40,7 +47,7 @@ fn default_page_size() -> i64 {
     20
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct DocumentListResponse {
     pub documents: Vec<DocumentSummary>,
     pub total: i64,
@@ -48,27 +55,33 @@ pub struct DocumentListResponse {
     pub page_size: i64,
 }
 
-#[derive(Debug, Serialize, sqlx::FromRow)]
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
 pub struct DocumentSummary {
     pub id: Uuid,
     pub document_type: String,
     pub document_number: String,
     pub title: String,
     pub status: String,
+    pub version_number: i32,
     pub generated_by_name: Option<String>,
     pub created_at: chrono::DateTime<chrono::Utc>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct ApproveDocumentRequest {
     pub comments: Option<String>,
+    /// Re-authentication for the approval signature (21 CFR Part 11)
+    pub password: String,
+    pub totp_code: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct DocumentVerificationResponse {
     pub document_id: Uuid,
     pub signature_valid: bool,
     pub ledger_valid: bool,
+    /// Electronic signature manifests (21 CFR Part 11)
+    pub e_signatures: SignatureVerification,
     pub overall_valid: bool,
     pub verified_at: chrono::DateTime<chrono::Utc>,
 }
@@ -85,6 +98,16 @@ pub struct DocumentVerificationResponse {
 /// 2. Generates document content using Claude AI
 /// 3. Signs document with user's Ed25519 private key
 /// 4. Creates immutable audit ledger entry
+#[utoipa::path(
+    post,
+    path = "/api/regulatory/documents/generate",
+    tag = "regulatory_documents",
+    summary = "Generate a regulatory document (CoA, GDP, GMP) using RAG + Claude AI",
+    request_body = GenerateDocumentRequest,
+    responses(
+        (status = 200, description = "Success", body = GeneratedDocument),
+    )
+)]
 pub async fn generate_document(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -131,6 +154,19 @@ pub async fn generate_document(
 
 /// GET /api/regulatory/documents
 /// List regulatory documents with filtering and pagination
+#[utoipa::path(
+    get,
+    path = "/api/regulatory/documents",
+    tag = "regulatory_documents",
+    operation_id = "regulatory_documents_list_documents",
+    summary = "List regulatory documents with filtering and pagination",
+    params(
+        ListDocumentsQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = DocumentListResponse),
+    )
+)]
 pub async fn list_documents(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -197,6 +233,7 @@ pub async fn list_documents(
             rd.document_number,
             rd.title,
             rd.status,
+            rd.version_number,
             rd.created_at,
             u.email as generated_by_name
         FROM regulatory_documents rd
@@ -254,6 +291,18 @@ pub async fn list_documents(
 
 /// GET /api/regulatory/documents/:id
 /// Get a single regulatory document by ID
+#[utoipa::path(
+    get,
+    path = "/api/regulatory/documents/{id}",
+    tag = "regulatory_documents",
+    summary = "Get a single regulatory document by ID",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
 pub async fn get_document(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -277,6 +326,17 @@ pub async fn get_document(
             rd.generated_by,
             rd.approved_by as "approved_by?",
             rd.approved_at as "approved_at?",
+            rd.catalog_version_id as "catalog_version_id?",
+            rd.unresolved_citations,
+            rd.root_document_id,
+            rd.version_number,
+            rd.previous_version_id as "previous_version_id?",
+            rd.change_reason as "change_reason?",
+            rd.superseded_by as "superseded_by?",
+            rd.superseded_at as "superseded_at?",
+            rd.obsoleted_by as "obsoleted_by?",
+            rd.obsolete_reason as "obsolete_reason?",
+            rd.obsoleted_at as "obsoleted_at?",
             rd.created_at,
             rd.updated_at,
             u1.email as "generated_by_name?",
@@ -294,6 +354,38 @@ pub async fn get_document(
     .fetch_one(&config.database_pool)
     .await?;
 
+    // Catalog version of the product at generation time (fixed even if the catalog changed since)
+    let catalog_version = match doc.catalog_version_id {
+        Some(version_id) => sqlx::query!(
+            r#"
+            SELECT id, product_ndc, version, data, valid_from, valid_to as "valid_to?"
+            FROM openfda_catalog_history
+            WHERE id = $1
+            "#,
+            version_id
+        )
+        .fetch_optional(&config.database_pool)
+        .await?
+        .map(|v| serde_json::json!({
+            "id": v.id,
+            "product_ndc": v.product_ndc,
+            "version": v.version,
+            "data": v.data,
+            "valid_from": v.valid_from,
+            "valid_to": v.valid_to,
+        })),
+        None => None,
+    };
+
+    // RAG citations with their provenance, flagged when the source chunk changed since generation
+    let citations = load_document_citations(&config.database_pool, document_id).await?;
+    let provenance = serde_json::json!({
+        "chunks_retrieved": citations.len(),
+        "chunks_cited": citations.iter().filter(|c| c.cited).count(),
+        "unresolved_citations": doc.unresolved_citations,
+        "sources_changed": citations.iter().filter(|c| c.source_changed).count(),
+    });
+
     // Fetch audit ledger
     let ledger = sqlx::query!(
         r#"
@@ -342,6 +434,20 @@ pub async fn get_document(
         "approved_signature": doc.approved_signature,
         "approved_at": doc.approved_at,
         "rag_context": doc.rag_context,
+        "citations": citations,
+        "provenance": provenance,
+        "catalog_version": catalog_version,
+        "version": {
+            "root_document_id": doc.root_document_id,
+            "version_number": doc.version_number,
+            "previous_version_id": doc.previous_version_id,
+            "change_reason": doc.change_reason,
+            "superseded_by": doc.superseded_by,
+            "superseded_at": doc.superseded_at,
+            "obsoleted_by": doc.obsoleted_by,
+            "obsolete_reason": doc.obsolete_reason,
+            "obsoleted_at": doc.obsoleted_at,
+        },
         "created_at": doc.created_at,
         "updated_at": doc.updated_at,
         "audit_ledger": ledger.iter().map(|entry| serde_json::json!({
@@ -360,12 +466,26 @@ pub async fn get_document(
 }
 
 /// POST /api/regulatory/documents/:id/approve
-/// Approve a regulatory document (adds approval signature)
+/// Approve a regulatory document: an approval e-signature with re-authentication. For a
+/// document with a signing sequence this is the caller's approval slot.
+#[utoipa::path(
+    post,
+    path = "/api/regulatory/documents/{id}/approve",
+    tag = "regulatory_documents",
+    summary = "Approve a regulatory document: an approval e-signature with re-authentication. For a",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    request_body = ApproveDocumentRequest,
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
 pub async fn approve_document(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
     Path(document_id): Path<Uuid>,
-    Json(_request): Json<ApproveDocumentRequest>,
+    Json(request): Json<ApproveDocumentRequest>,
 ) -> Result<Json<serde_json::Value>> {
     tracing::info!(
         "User {} approving document {}",
@@ -373,39 +493,248 @@ pub async fn approve_document(
         document_id
     );
 
-    // Get API key from environment
-    let anthropic_api_key = std::env::var("ANTHROPIC_API_KEY")
-        .map_err(|_| anyhow::anyhow!("ANTHROPIC_API_KEY not configured"))?;
-
-    // Create regulatory document generator
-    let generator = RegulatoryDocumentGenerator::new(
-        config.database_pool.clone(),
-        anthropic_api_key,
-        &config.encryption_key,
-        claims.user_id,  // Use actual user for quota tracking
-    )?;
-
-    // Approve document with Ed25519 signature
-    generator
-        .approve_document(document_id, claims.user_id)
+    let service = ElectronicSignatureService::new(config.database_pool.clone(), &config.encryption_key)?;
+    let status = service
+        .sign(
+            document_id,
+            claims.user_id,
+            SignDocumentRequest {
+                password: request.password,
+                totp_code: request.totp_code,
+                meaning: SignatureMeaning::Approval,
+            },
+        )
         .await?;
 
     tracing::info!(
-        "Audit: User {} approved document {}",
+        "Audit: User {} approved document {} (comments: {:?})",
         claims.user_id,
-        document_id
+        document_id,
+        request.comments
     );
 
     Ok(Json(serde_json::json!({
         "success": true,
         "document_id": document_id,
+        "document_status": status.document_status,
         "approved_by": claims.user_id,
         "approved_at": chrono::Utc::now(),
+        "signatures": status.signatures,
     })))
 }
 
+/// PUT /api/regulatory/documents/:id/signers
+/// Set the signing sequence (signers in order, each with a meaning); author only
+#[utoipa::path(
+    put,
+    path = "/api/regulatory/documents/{id}/signers",
+    tag = "regulatory_documents",
+    summary = "Set the signing sequence (signers in order, each with a meaning); author only",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    request_body = SetSigningSequenceRequest,
+    responses(
+        (status = 200, description = "Success", body = SigningStatus),
+    )
+)]
+pub async fn set_document_signers(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(document_id): Path<Uuid>,
+    Json(request): Json<SetSigningSequenceRequest>,
+) -> Result<Json<SigningStatus>> {
+    request.validate().map_err(AppError::Validation)?;
+
+    let service = ElectronicSignatureService::new(config.database_pool.clone(), &config.encryption_key)?;
+    let status = service.set_sequence(document_id, claims.user_id, request).await?;
+
+    tracing::info!(
+        "Audit: User {} set the signing sequence of document {}",
+        claims.user_id,
+        document_id
+    );
+
+    Ok(Json(status))
+}
+
+/// GET /api/regulatory/documents/:id/signatures
+/// Signing sequence with the signature manifests collected so far
+#[utoipa::path(
+    get,
+    path = "/api/regulatory/documents/{id}/signatures",
+    tag = "regulatory_documents",
+    summary = "Signing sequence with the signature manifests collected so far",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = SigningStatus),
+    )
+)]
+pub async fn get_document_signatures(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(document_id): Path<Uuid>,
+) -> Result<Json<SigningStatus>> {
+    let service = ElectronicSignatureService::new(config.database_pool.clone(), &config.encryption_key)?;
+    Ok(Json(service.status(document_id, claims.user_id).await?))
+}
+
+/// POST /api/regulatory/documents/:id/sign
+/// Sign as the next signer in the sequence (re-authentication required)
+#[utoipa::path(
+    post,
+    path = "/api/regulatory/documents/{id}/sign",
+    tag = "regulatory_documents",
+    summary = "Sign as the next signer in the sequence (re-authentication required)",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    request_body = SignDocumentRequest,
+    responses(
+        (status = 200, description = "Success", body = SigningStatus),
+    )
+)]
+pub async fn sign_document(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(document_id): Path<Uuid>,
+    Json(request): Json<SignDocumentRequest>,
+) -> Result<Json<SigningStatus>> {
+    request.validate().map_err(AppError::Validation)?;
+
+    let meaning = request.meaning;
+    let service = ElectronicSignatureService::new(config.database_pool.clone(), &config.encryption_key)?;
+    let status = service.sign(document_id, claims.user_id, request).await?;
+
+    tracing::info!(
+        "Audit: User {} signed document {} ({})",
+        claims.user_id,
+        document_id,
+        meaning.as_str()
+    );
+
+    Ok(Json(status))
+}
+
+/// POST /api/regulatory/documents/:id/revise
+/// Create the next version of an approved or rejected document, with the reason for the
+/// change; the new version is a draft that goes through signing again
+#[utoipa::path(
+    post,
+    path = "/api/regulatory/documents/{id}/revise",
+    tag = "regulatory_documents",
+    summary = "Create the next version of an approved or rejected document, with the reason for the",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    request_body = ReviseDocumentRequest,
+    responses(
+        (status = 200, description = "Success", body = DocumentVersion),
+    )
+)]
+pub async fn revise_document(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(document_id): Path<Uuid>,
+    Json(request): Json<ReviseDocumentRequest>,
+) -> Result<Json<DocumentVersion>> {
+    request.validate().map_err(AppError::Validation)?;
+
+    let service = DocumentVersionService::new(config.database_pool.clone(), &config.encryption_key)?;
+    Ok(Json(service.revise(document_id, claims.user_id, request).await?))
+}
+
+/// POST /api/regulatory/documents/:id/obsolete
+/// Withdraw an approved document without a replacement
+#[utoipa::path(
+    post,
+    path = "/api/regulatory/documents/{id}/obsolete",
+    tag = "regulatory_documents",
+    summary = "Withdraw an approved document without a replacement",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    request_body = ObsoleteDocumentRequest,
+    responses(
+        (status = 200, description = "Success", body = DocumentVersion),
+    )
+)]
+pub async fn obsolete_document(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(document_id): Path<Uuid>,
+    Json(request): Json<ObsoleteDocumentRequest>,
+) -> Result<Json<DocumentVersion>> {
+    request.validate().map_err(AppError::Validation)?;
+
+    let service = DocumentVersionService::new(config.database_pool.clone(), &config.encryption_key)?;
+    Ok(Json(service.obsolete(document_id, claims.user_id, request).await?))
+}
+
+/// GET /api/regulatory/documents/:id/versions
+/// All versions of the document, oldest first, and the version currently in force
+#[utoipa::path(
+    get,
+    path = "/api/regulatory/documents/{id}/versions",
+    tag = "regulatory_documents",
+    summary = "All versions of the document, oldest first, and the version currently in force",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = DocumentVersionHistory),
+    )
+)]
+pub async fn get_document_versions(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(document_id): Path<Uuid>,
+) -> Result<Json<DocumentVersionHistory>> {
+    let service = DocumentVersionService::new(config.database_pool.clone(), &config.encryption_key)?;
+    Ok(Json(service.history(document_id, claims.user_id).await?))
+}
+
+/// GET /api/regulatory/documents/:id/diff
+/// Content changes from the previous version (or `?against=` another version) to this one
+#[utoipa::path(
+    get,
+    path = "/api/regulatory/documents/{id}/diff",
+    tag = "regulatory_documents",
+    summary = "Content changes from the previous version (or `?against=` another version) to this one",
+    params(
+        ("id" = Uuid, Path),
+        DocumentDiffQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = DocumentDiff),
+    )
+)]
+pub async fn get_document_diff(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(document_id): Path<Uuid>,
+    Query(query): Query<DocumentDiffQuery>,
+) -> Result<Json<DocumentDiff>> {
+    let service = DocumentVersionService::new(config.database_pool.clone(), &config.encryption_key)?;
+    Ok(Json(service.diff(document_id, claims.user_id, query.against).await?))
+}
+
 /// GET /api/regulatory/documents/:id/verify
 /// Verify document signature and ledger chain integrity
+#[utoipa::path(
+    get,
+    path = "/api/regulatory/documents/{id}/verify",
+    tag = "regulatory_documents",
+    summary = "Verify document signature and ledger chain integrity",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = DocumentVerificationResponse),
+    )
+)]
 pub async fn verify_document(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -428,23 +757,44 @@ pub async fn verify_document(
     // Verify document (signature + ledger chain)
     let is_valid = generator.verify_document(document_id).await?;
 
+    // Verify every electronic signature manifest against the current content
+    let e_signatures = ElectronicSignatureService::new(config.database_pool.clone(), &config.encryption_key)?
+        .verify(document_id)
+        .await?;
+
     tracing::info!(
-        "Document {} verification result: {}",
+        "Document {} verification result: {} ({} e-signatures, valid: {})",
         document_id,
-        is_valid
+        is_valid,
+        e_signatures.signature_count,
+        e_signatures.valid
     );
 
+    let overall_valid = is_valid && e_signatures.valid;
     Ok(Json(DocumentVerificationResponse {
         document_id,
         signature_valid: is_valid,
         ledger_valid: is_valid,
-        overall_valid: is_valid,
+        e_signatures,
+        overall_valid,
         verified_at: chrono::Utc::now(),
     }))
 }
 
 /// GET /api/regulatory/documents/:id/audit-trail
 /// Get complete audit trail for a document
+#[utoipa::path(
+    get,
+    path = "/api/regulatory/documents/{id}/audit-trail",
+    tag = "regulatory_documents",
+    summary = "Get complete audit trail for a document",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
 pub async fn get_audit_trail(
     State(config): State<AppConfig>,
     Extension(claims): Extension<Claims>,
@@ -505,6 +855,15 @@ pub async fn get_audit_trail(
 
 /// GET /api/regulatory/knowledge-base/stats
 /// Get statistics about the regulatory knowledge base
+#[utoipa::path(
+    get,
+    path = "/api/regulatory/knowledge-base/stats",
+    tag = "regulatory_documents",
+    summary = "Get statistics about the regulatory knowledge base",
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
 pub async fn get_knowledge_base_stats(
     State(config): State<AppConfig>,
     Extension(_claims): Extension<Claims>,
@@ -0,0 +1,348 @@
+/// Regulatory Knowledge-Base Management REST API Handlers
+///
+/// CRUD for the RAG knowledge base used by regulatory document generation, bulk import
+/// of regulation texts (chunked and embedded) and per-source version history.
+/// Reads are open to authenticated users; changes require an admin.
+
+use axum::{
+    extract::{Multipart, Path, Query, State},
+    Extension, Json,
+};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    config::AppConfig,
+    middleware::{error_handling::{AppError, Result}, Claims},
+    models::knowledge_base::*,
+    services::KnowledgeBaseService,
+};
+
+/// Maximum size of an uploaded regulation text
+const MAX_IMPORT_FILE_SIZE: usize = 5 * 1024 * 1024;
+
+fn knowledge_base_service(config: &AppConfig, claims: &Claims) -> Result<KnowledgeBaseService> {
+    let anthropic_api_key = std::env::var("ANTHROPIC_API_KEY")
+        .map_err(|_| AppError::Internal(anyhow::anyhow!("ANTHROPIC_API_KEY not configured")))?;
+
+    KnowledgeBaseService::new(config.database_pool.clone(), anthropic_api_key, claims.user_id)
+}
+
+// ============================================================================
+// ENTRY ENDPOINTS
+// ============================================================================
+
+/// GET /api/regulatory/knowledge-base
+/// List knowledge-base entries with filtering and pagination
+#[utoipa::path(
+    get,
+    path = "/api/regulatory/knowledge-base",
+    tag = "regulatory_knowledge_base",
+    summary = "List knowledge-base entries with filtering and pagination",
+    params(
+        ListKnowledgeEntriesQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = KnowledgeEntryListResponse),
+    )
+)]
+pub async fn list_knowledge_entries(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<ListKnowledgeEntriesQuery>,
+) -> Result<Json<KnowledgeEntryListResponse>> {
+    let service = knowledge_base_service(&config, &claims)?;
+    Ok(Json(service.list_entries(&query).await?))
+}
+
+/// GET /api/regulatory/knowledge-base/:id
+#[utoipa::path(
+    get,
+    path = "/api/regulatory/knowledge-base/{id}",
+    tag = "regulatory_knowledge_base",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = KnowledgeBaseEntry),
+    )
+)]
+pub async fn get_knowledge_entry(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(entry_id): Path<Uuid>,
+) -> Result<Json<KnowledgeBaseEntry>> {
+    let service = knowledge_base_service(&config, &claims)?;
+    Ok(Json(service.get_entry(entry_id).await?))
+}
+
+/// POST /api/regulatory/knowledge-base
+/// Create an entry (embedded on creation) as a new version of its regulation source
+#[utoipa::path(
+    post,
+    path = "/api/regulatory/knowledge-base",
+    tag = "regulatory_knowledge_base",
+    summary = "Create an entry (embedded on creation) as a new version of its regulation source",
+    request_body = CreateKnowledgeEntryRequest,
+    responses(
+        (status = 200, description = "Success", body = KnowledgeBaseEntry),
+    )
+)]
+pub async fn create_knowledge_entry(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<CreateKnowledgeEntryRequest>,
+) -> Result<Json<KnowledgeBaseEntry>> {
+    crate::require_admin!(claims);
+    request.validate().map_err(AppError::Validation)?;
+
+    let service = knowledge_base_service(&config, &claims)?;
+    let entry = service.create_entry(&request, claims.user_id).await?;
+
+    tracing::info!("Audit: User {} created knowledge entry {}", claims.user_id, entry.id);
+
+    Ok(Json(entry))
+}
+
+/// PUT /api/regulatory/knowledge-base/:id
+/// Update an entry; changed content is re-embedded
+#[utoipa::path(
+    put,
+    path = "/api/regulatory/knowledge-base/{id}",
+    tag = "regulatory_knowledge_base",
+    summary = "Update an entry; changed content is re-embedded",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    request_body = UpdateKnowledgeEntryRequest,
+    responses(
+        (status = 200, description = "Success", body = KnowledgeBaseEntry),
+    )
+)]
+pub async fn update_knowledge_entry(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(entry_id): Path<Uuid>,
+    Json(request): Json<UpdateKnowledgeEntryRequest>,
+) -> Result<Json<KnowledgeBaseEntry>> {
+    crate::require_admin!(claims);
+    request.validate().map_err(AppError::Validation)?;
+
+    let service = knowledge_base_service(&config, &claims)?;
+    let entry = service.update_entry(entry_id, &request, claims.user_id).await?;
+
+    tracing::info!("Audit: User {} updated knowledge entry {}", claims.user_id, entry_id);
+
+    Ok(Json(entry))
+}
+
+/// DELETE /api/regulatory/knowledge-base/:id
+#[utoipa::path(
+    delete,
+    path = "/api/regulatory/knowledge-base/{id}",
+    tag = "regulatory_knowledge_base",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
+pub async fn delete_knowledge_entry(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(entry_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>> {
+    crate::require_admin!(claims);
+
+    let service = knowledge_base_service(&config, &claims)?;
+    service.delete_entry(entry_id, claims.user_id).await?;
+
+    tracing::info!("Audit: User {} deleted knowledge entry {}", claims.user_id, entry_id);
+
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "entry_id": entry_id,
+    })))
+}
+
+/// GET /api/regulatory/knowledge-base/:id/history
+/// Every recorded state of an entry, newest first
+#[utoipa::path(
+    get,
+    path = "/api/regulatory/knowledge-base/{id}/history",
+    tag = "regulatory_knowledge_base",
+    summary = "Every recorded state of an entry, newest first",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<KnowledgeEntryVersion>),
+    )
+)]
+pub async fn get_knowledge_entry_history(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(entry_id): Path<Uuid>,
+) -> Result<Json<Vec<KnowledgeEntryVersion>>> {
+    let service = knowledge_base_service(&config, &claims)?;
+    Ok(Json(service.entry_history(entry_id).await?))
+}
+
+// ============================================================================
+// BULK IMPORT
+// ============================================================================
+
+/// POST /api/regulatory/knowledge-base/import
+/// Upload a regulation text (UTF-8 text or markdown), chunk it by section and store the
+/// chunks as embedded entries under one new version of the regulation source.
+///
+/// Form fields: `file`, `document_type`, `regulation_source`, optional
+/// `regulation_section`, `replace_existing`, `chunk_chars` and `notes`.
+#[utoipa::path(
+    post,
+    path = "/api/regulatory/knowledge-base/import",
+    tag = "regulatory_knowledge_base",
+    summary = "Upload a regulation text (UTF-8 text or markdown), chunk it by section and store the",
+    request_body(content = crate::openapi::KnowledgeDocumentForm, content_type = "multipart/form-data"),
+    responses(
+        (status = 200, description = "Success", body = KnowledgeImportResult),
+    )
+)]
+pub async fn import_knowledge_document(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    mut multipart: Multipart,
+) -> Result<Json<KnowledgeImportResult>> {
+    crate::require_admin!(claims);
+
+    let mut file_data: Option<Vec<u8>> = None;
+    let mut filename: Option<String> = None;
+    let mut options = KnowledgeImportOptions::default();
+
+    while let Some(field) = multipart.next_field().await.map_err(|e| {
+        AppError::InvalidInput(format!("Invalid multipart data: {}", e))
+    })? {
+        let field_name = field.name().unwrap_or("").to_string();
+
+        if field_name == "file" {
+            filename = field.file_name().map(|s| s.to_string());
+            file_data = Some(field.bytes().await.map_err(|e| {
+                AppError::InvalidInput(format!("Failed to read file: {}", e))
+            })?.to_vec());
+            continue;
+        }
+
+        let value = field.text().await.map_err(|e| {
+            AppError::InvalidInput(format!("Invalid {} field: {}", field_name, e))
+        })?;
+        let value = value.trim().to_string();
+
+        match field_name.as_str() {
+            "document_type" => options.document_type = value,
+            "regulation_source" => options.regulation_source = value,
+            "regulation_section" => {
+                if value.chars().count() > 100 {
+                    return Err(AppError::InvalidInput(
+                        "regulation_section must be at most 100 characters".to_string()
+                    ));
+                }
+                options.regulation_section = Some(value).filter(|v| !v.is_empty());
+            }
+            "replace_existing" => options.replace_existing = matches!(value.as_str(), "true" | "1"),
+            "chunk_chars" => {
+                let chunk_chars = value.parse::<usize>().map_err(|_| {
+                    AppError::InvalidInput("chunk_chars must be a positive number".to_string())
+                })?;
+                if !(MIN_CHUNK_CHARS..=MAX_CHUNK_CHARS).contains(&chunk_chars) {
+                    return Err(AppError::InvalidInput(format!(
+                        "chunk_chars must be between {} and {}",
+                        MIN_CHUNK_CHARS, MAX_CHUNK_CHARS
+                    )));
+                }
+                options.chunk_chars = Some(chunk_chars);
+            }
+            "notes" => options.notes = Some(value).filter(|v| !v.is_empty()),
+            _ => {}
+        }
+    }
+
+    let file_data = file_data.ok_or_else(|| AppError::InvalidInput("No file provided".to_string()))?;
+    if file_data.len() > MAX_IMPORT_FILE_SIZE {
+        return Err(AppError::InvalidInput(format!(
+            "File too large. Maximum size is {}MB",
+            MAX_IMPORT_FILE_SIZE / 1024 / 1024
+        )));
+    }
+
+    let text = String::from_utf8(file_data).map_err(|_| {
+        AppError::InvalidInput("Only UTF-8 text or markdown files can be imported".to_string())
+    })?;
+
+    tracing::info!(
+        "User {} importing knowledge document {} into {}",
+        claims.user_id,
+        crate::utils::log_sanitizer::sanitize_for_log(filename.as_deref().unwrap_or("(unnamed)")),
+        crate::utils::log_sanitizer::sanitize_for_log(&options.regulation_source)
+    );
+
+    let service = knowledge_base_service(&config, &claims)?;
+    let result = service.import_document(&text, &options, claims.user_id).await?;
+
+    tracing::info!(
+        "Audit: User {} imported {} knowledge entries into {} v{} ({} replaced)",
+        claims.user_id,
+        result.entries_created,
+        crate::utils::log_sanitizer::sanitize_for_log(&result.regulation_source),
+        result.source_version,
+        result.entries_replaced
+    );
+
+    Ok(Json(result))
+}
+
+// ============================================================================
+// SOURCE VERSIONING
+// ============================================================================
+
+/// GET /api/regulatory/knowledge-base/sources
+/// Regulation sources with their current version and entry count
+#[utoipa::path(
+    get,
+    path = "/api/regulatory/knowledge-base/sources",
+    tag = "regulatory_knowledge_base",
+    summary = "Regulation sources with their current version and entry count",
+    responses(
+        (status = 200, description = "Success", body = Vec<KnowledgeSource>),
+    )
+)]
+pub async fn list_knowledge_sources(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Vec<KnowledgeSource>>> {
+    let service = knowledge_base_service(&config, &claims)?;
+    Ok(Json(service.list_sources().await?))
+}
+
+/// GET /api/regulatory/knowledge-base/sources/versions?regulation_source=...
+/// Version history of one regulation source, newest first
+#[utoipa::path(
+    get,
+    path = "/api/regulatory/knowledge-base/sources/versions",
+    tag = "regulatory_knowledge_base",
+    summary = "Version history of one regulation source, newest first",
+    params(
+        SourceVersionsQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<KnowledgeSourceVersion>),
+    )
+)]
+pub async fn list_knowledge_source_versions(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<SourceVersionsQuery>,
+) -> Result<Json<Vec<KnowledgeSourceVersion>>> {
+    let service = knowledge_base_service(&config, &claims)?;
+    Ok(Json(service.list_source_versions(&query.regulation_source).await?))
+}
@@ -0,0 +1,219 @@
+/// Regulatory Rules REST API Handlers
+///
+/// Admins configure jurisdiction rules (product authorization in the buyer's country,
+/// licenses and permits the parties must hold); the marketplace evaluates them when
+/// inquiries and transactions are created. Buyers can check a listing's requirements
+/// before inquiring.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use uuid::Uuid;
+use validator::Validate;
+use crate::{
+    config::AppConfig,
+    middleware::{error_handling::{AppError, Result}, Claims},
+    models::regulatory_rule::*,
+    services::RegulatoryRulesService,
+};
+
+/// POST /api/marketplace/regulatory-check
+/// Requirements for the current user buying a listing
+#[utoipa::path(
+    post,
+    path = "/api/marketplace/regulatory-check",
+    tag = "regulatory_rules",
+    summary = "Requirements for the current user buying a listing",
+    request_body = RegulatoryCheckRequest,
+    responses(
+        (status = 200, description = "Success", body = RegulatoryEvaluation),
+    )
+)]
+pub async fn check_requirements(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<RegulatoryCheckRequest>,
+) -> Result<Json<RegulatoryEvaluation>> {
+    let service = RegulatoryRulesService::new(config.database_pool.clone());
+    Ok(Json(service.evaluate(request.inventory_id, claims.user_id).await?))
+}
+
+/// GET /api/marketplace/inquiries/:id/regulatory-requirements
+/// Latest evaluation of an inquiry or its transaction
+#[utoipa::path(
+    get,
+    path = "/api/marketplace/inquiries/{id}/regulatory-requirements",
+    tag = "regulatory_rules",
+    summary = "Latest evaluation of an inquiry or its transaction",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = RegulatoryRuleEvaluation),
+    )
+)]
+pub async fn get_inquiry_requirements(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(inquiry_id): Path<Uuid>,
+) -> Result<Json<RegulatoryRuleEvaluation>> {
+    let service = RegulatoryRulesService::new(config.database_pool.clone());
+    Ok(Json(service.latest_for_inquiry(inquiry_id, claims.user_id).await?))
+}
+
+/// GET /api/admin/regulatory-rules
+/// Active rules (`?include_inactive=true` for all)
+#[utoipa::path(
+    get,
+    path = "/api/admin/regulatory-rules",
+    tag = "regulatory_rules",
+    summary = "Active rules (`?include_inactive=true` for all)",
+    params(
+        RegulatoryRuleQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<RegulatoryRule>),
+    )
+)]
+pub async fn list_rules(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<RegulatoryRuleQuery>,
+) -> Result<Json<Vec<RegulatoryRule>>> {
+    crate::require_admin!(claims);
+
+    let service = RegulatoryRulesService::new(config.database_pool.clone());
+    Ok(Json(service.list_rules(query.include_inactive.unwrap_or(false)).await?))
+}
+
+/// GET /api/admin/regulatory-rules/:id
+#[utoipa::path(
+    get,
+    path = "/api/admin/regulatory-rules/{id}",
+    tag = "regulatory_rules",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = RegulatoryRule),
+    )
+)]
+pub async fn get_rule(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(rule_id): Path<Uuid>,
+) -> Result<Json<RegulatoryRule>> {
+    crate::require_admin!(claims);
+
+    let service = RegulatoryRulesService::new(config.database_pool.clone());
+    Ok(Json(service.get_rule(rule_id).await?))
+}
+
+/// POST /api/admin/regulatory-rules
+#[utoipa::path(
+    post,
+    path = "/api/admin/regulatory-rules",
+    tag = "regulatory_rules",
+    request_body = RegulatoryRuleRequest,
+    responses(
+        (status = 201, description = "Created", body = RegulatoryRule),
+    )
+)]
+pub async fn create_rule(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<RegulatoryRuleRequest>,
+) -> Result<(StatusCode, Json<RegulatoryRule>)> {
+    crate::require_admin!(claims);
+    request.validate().map_err(AppError::Validation)?;
+
+    let service = RegulatoryRulesService::new(config.database_pool.clone());
+    let rule = service.create_rule(request, claims.user_id).await?;
+
+    tracing::info!("Audit: Admin {} created regulatory rule {} ({})", claims.user_id, rule.id, rule.name);
+    Ok((StatusCode::CREATED, Json(rule)))
+}
+
+/// PUT /api/admin/regulatory-rules/:id
+/// Replace a rule; `is_active: false` deactivates it
+#[utoipa::path(
+    put,
+    path = "/api/admin/regulatory-rules/{id}",
+    tag = "regulatory_rules",
+    summary = "Replace a rule; `is_active: false` deactivates it",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    request_body = RegulatoryRuleRequest,
+    responses(
+        (status = 200, description = "Success", body = RegulatoryRule),
+    )
+)]
+pub async fn update_rule(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(rule_id): Path<Uuid>,
+    Json(request): Json<RegulatoryRuleRequest>,
+) -> Result<Json<RegulatoryRule>> {
+    crate::require_admin!(claims);
+    request.validate().map_err(AppError::Validation)?;
+
+    let service = RegulatoryRulesService::new(config.database_pool.clone());
+    let rule = service.update_rule(rule_id, request, claims.user_id).await?;
+
+    tracing::info!("Audit: Admin {} updated regulatory rule {} ({})", claims.user_id, rule.id, rule.name);
+    Ok(Json(rule))
+}
+
+/// DELETE /api/admin/regulatory-rules/:id
+#[utoipa::path(
+    delete,
+    path = "/api/admin/regulatory-rules/{id}",
+    tag = "regulatory_rules",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 204, description = "No content"),
+    )
+)]
+pub async fn delete_rule(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(rule_id): Path<Uuid>,
+) -> Result<StatusCode> {
+    crate::require_admin!(claims);
+
+    let service = RegulatoryRulesService::new(config.database_pool.clone());
+    service.delete_rule(rule_id).await?;
+
+    tracing::info!("Audit: Admin {} deleted regulatory rule {}", claims.user_id, rule_id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /api/admin/regulatory-rules/evaluations
+/// Stored evaluations (`?blocked=true` for refused trades, `?user_id=` for one party)
+#[utoipa::path(
+    get,
+    path = "/api/admin/regulatory-rules/evaluations",
+    tag = "regulatory_rules",
+    summary = "Stored evaluations (`?blocked=true` for refused trades, `?user_id=` for one party)",
+    params(
+        RegulatoryEvaluationQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<RegulatoryRuleEvaluation>),
+    )
+)]
+pub async fn list_evaluations(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<RegulatoryEvaluationQuery>,
+) -> Result<Json<Vec<RegulatoryRuleEvaluation>>> {
+    crate::require_admin!(claims);
+
+    let service = RegulatoryRulesService::new(config.database_pool.clone());
+    Ok(Json(service.list_evaluations(&query).await?))
+}
@@ -0,0 +1,223 @@
+/// Sanctions Screening REST API Handlers (admin only)
+///
+/// Review queue of sanctions screenings with potential matches, manual screening of a
+/// user, and status and manual sync of the OFAC SDN and EU consolidated lists.
+/// Screening itself runs automatically at registration and before a transaction
+/// completes.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use uuid::Uuid;
+use validator::Validate;
+use crate::{
+    config::AppConfig,
+    middleware::{error_handling::{AppError, Result}, Claims},
+    models::{background_job::{JobType, NewJob, SanctionsListSyncJob}, sanctions::*},
+    services::{BackgroundJobService, SanctionsListService, SanctionsScreeningService},
+};
+
+/// GET /api/admin/sanctions/screenings
+/// Screenings awaiting review (`?status=all` for all, `?user_id=`, `?transaction_id=`)
+#[utoipa::path(
+    get,
+    path = "/api/admin/sanctions/screenings",
+    tag = "sanctions",
+    summary = "Screenings awaiting review (`?status=all` for all, `?user_id=`, `?transaction_id=`)",
+    params(
+        ScreeningQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<SanctionsScreening>),
+    )
+)]
+pub async fn list_screenings(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<ScreeningQuery>,
+) -> Result<Json<Vec<SanctionsScreening>>> {
+    crate::require_admin!(claims);
+
+    let service = SanctionsScreeningService::new(config.database_pool.clone(), &config.encryption_key)?;
+    Ok(Json(service.list(&query).await?))
+}
+
+/// GET /api/admin/sanctions/screenings/:id
+#[utoipa::path(
+    get,
+    path = "/api/admin/sanctions/screenings/{id}",
+    tag = "sanctions",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = SanctionsScreening),
+    )
+)]
+pub async fn get_screening(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(screening_id): Path<Uuid>,
+) -> Result<Json<SanctionsScreening>> {
+    crate::require_admin!(claims);
+
+    let service = SanctionsScreeningService::new(config.database_pool.clone(), &config.encryption_key)?;
+    Ok(Json(service.get(screening_id).await?))
+}
+
+/// POST /api/admin/sanctions/screenings/:id/review
+/// Clear a pending screening as a false positive or confirm the match
+#[utoipa::path(
+    post,
+    path = "/api/admin/sanctions/screenings/{id}/review",
+    tag = "sanctions",
+    summary = "Clear a pending screening as a false positive or confirm the match",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    request_body = ReviewScreeningRequest,
+    responses(
+        (status = 200, description = "Success", body = SanctionsScreening),
+    )
+)]
+pub async fn review_screening(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(screening_id): Path<Uuid>,
+    Json(request): Json<ReviewScreeningRequest>,
+) -> Result<Json<SanctionsScreening>> {
+    crate::require_admin!(claims);
+    request.validate().map_err(AppError::Validation)?;
+
+    let service = SanctionsScreeningService::new(config.database_pool.clone(), &config.encryption_key)?;
+    let screening = service.review(screening_id, claims.user_id, request).await?;
+
+    tracing::info!(
+        "Audit: Admin {} reviewed sanctions screening {} of user {} as {}",
+        claims.user_id,
+        screening.id,
+        screening.user_id,
+        screening.status
+    );
+
+    Ok(Json(screening))
+}
+
+/// POST /api/admin/sanctions/users/:id/screen
+/// Screen a user now, e.g. after a list update or a company name change
+#[utoipa::path(
+    post,
+    path = "/api/admin/sanctions/users/{id}/screen",
+    tag = "sanctions",
+    summary = "Screen a user now, e.g. after a list update or a company name change",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 201, description = "Created", body = SanctionsScreening),
+    )
+)]
+pub async fn screen_user(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(user_id): Path<Uuid>,
+) -> Result<(StatusCode, Json<SanctionsScreening>)> {
+    crate::require_admin!(claims);
+
+    let service = SanctionsScreeningService::new(config.database_pool.clone(), &config.encryption_key)?;
+    let screening = service.screen_user(user_id, ScreeningTrigger::Manual, None).await?;
+
+    tracing::info!(
+        "Audit: Admin {} screened user {} against the sanctions lists: {}",
+        claims.user_id,
+        user_id,
+        screening.outcome
+    );
+
+    Ok((StatusCode::CREATED, Json(screening)))
+}
+
+/// GET /api/admin/sanctions/lists
+/// Entry count and latest sync of each sanctions list
+#[utoipa::path(
+    get,
+    path = "/api/admin/sanctions/lists",
+    tag = "sanctions",
+    summary = "Entry count and latest sync of each sanctions list",
+    responses(
+        (status = 200, description = "Success", body = Vec<SanctionsListStatus>),
+    )
+)]
+pub async fn get_list_status(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Vec<SanctionsListStatus>>> {
+    crate::require_admin!(claims);
+
+    let service = SanctionsListService::new(config.database_pool.clone());
+    Ok(Json(service.status().await?))
+}
+
+/// GET /api/admin/sanctions/lists/syncs
+#[utoipa::path(
+    get,
+    path = "/api/admin/sanctions/lists/syncs",
+    tag = "sanctions",
+    responses(
+        (status = 200, description = "Success", body = Vec<SanctionsListSync>),
+    )
+)]
+pub async fn list_syncs(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Vec<SanctionsListSync>>> {
+    crate::require_admin!(claims);
+
+    let service = SanctionsListService::new(config.database_pool.clone());
+    Ok(Json(service.list_syncs(50).await?))
+}
+
+/// POST /api/admin/sanctions/lists/sync
+/// Sync one list (`source`) or all configured lists in the background
+#[utoipa::path(
+    post,
+    path = "/api/admin/sanctions/lists/sync",
+    tag = "sanctions",
+    summary = "Sync one list (`source`) or all configured lists in the background",
+    request_body = SyncSanctionsListsRequest,
+    responses(
+        (status = 202, description = "Accepted"),
+    )
+)]
+pub async fn sync_lists(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<SyncSanctionsListsRequest>,
+) -> Result<StatusCode> {
+    crate::require_admin!(claims);
+
+    if let Some(source) = request.source {
+        if !SanctionsListService::is_configured(source) {
+            return Err(AppError::BadRequest(format!("{} is not configured", source.label())));
+        }
+    }
+
+    tracing::info!("Audit: Admin {} started a sanctions list sync", claims.user_id);
+
+    let job = SanctionsListSyncJob { source: request.source, requested_by: claims.user_id };
+    let dedupe_key = match request.source {
+        Some(source) => format!("sanctions_list_sync:{}", source.as_str()),
+        None => "sanctions_list_sync:all".to_string(),
+    };
+    BackgroundJobService::new(config.database_pool.clone())
+        .enqueue(
+            NewJob::new(JobType::SanctionsListSync, &job)
+                .with_dedupe_key(dedupe_key)
+                .created_by(claims.user_id),
+        )
+        .await?;
+
+    Ok(StatusCode::ACCEPTED)
+}
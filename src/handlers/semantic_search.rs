@@ -0,0 +1,103 @@
+/// Semantic Search REST API Handlers
+///
+/// `GET /api/search/semantic` blends vector similarity with full-text ranking over
+/// pharmaceuticals, marketplace inventory and the OpenFDA / EMA catalogs. Admins start
+/// embedding backfills and follow their progress under `/api/admin/search`.
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use serde::Deserialize;
+use utoipa::IntoParams;
+use crate::{
+    config::AppConfig,
+    middleware::{error_handling::Result, Claims},
+    models::semantic_search::{
+        EmbeddingBackfillJob, SemanticSearchRequest, SemanticSearchResponse, StartEmbeddingBackfillRequest,
+    },
+    services::SemanticSearchService,
+};
+
+/// GET /api/search/semantic?q=&types=&semantic_weight=&limit=
+/// `types` narrows the record types (pharmaceutical, inventory, openfda, ema);
+/// `semantic_weight` sets the share of vector similarity in the score (default 0.6)
+#[utoipa::path(
+    get,
+    path = "/api/search/semantic",
+    tag = "semantic_search",
+    params(
+        SemanticSearchRequest,
+    ),
+    responses(
+        (status = 200, description = "Success", body = SemanticSearchResponse),
+    )
+)]
+pub async fn semantic_search(
+    State(config): State<AppConfig>,
+    Query(request): Query<SemanticSearchRequest>,
+) -> Result<Json<SemanticSearchResponse>> {
+    let service = SemanticSearchService::new(config.database_pool.clone())?;
+    Ok(Json(service.search(&request).await?))
+}
+
+/// POST /api/admin/search/embeddings/backfill
+/// Embed new and changed records (`full_rebuild` re-embeds everything) in the background
+#[utoipa::path(
+    post,
+    path = "/api/admin/search/embeddings/backfill",
+    tag = "semantic_search",
+    summary = "Embed new and changed records (`full_rebuild` re-embeds everything) in the background",
+    request_body = StartEmbeddingBackfillRequest,
+    responses(
+        (status = 202, description = "Accepted", body = EmbeddingBackfillJob),
+    )
+)]
+pub async fn start_embedding_backfill(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<StartEmbeddingBackfillRequest>,
+) -> Result<(StatusCode, Json<EmbeddingBackfillJob>)> {
+    crate::require_admin!(claims);
+
+    let job = SemanticSearchService::start_backfill(config.database_pool.clone(), &request, Some(claims.user_id)).await?;
+
+    tracing::info!(
+        "Embedding backfill {} started by {} (full rebuild: {})",
+        job.id, claims.user_id, job.full_rebuild
+    );
+
+    Ok((StatusCode::ACCEPTED, Json(job)))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EmbeddingJobsQuery {
+    pub limit: Option<i64>,
+}
+
+/// GET /api/admin/search/embeddings/jobs
+/// Most recent backfill jobs with their progress
+#[utoipa::path(
+    get,
+    path = "/api/admin/search/embeddings/jobs",
+    tag = "semantic_search",
+    summary = "Most recent backfill jobs with their progress",
+    params(
+        EmbeddingJobsQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<EmbeddingBackfillJob>),
+    )
+)]
+pub async fn list_embedding_jobs(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<EmbeddingJobsQuery>,
+) -> Result<Json<Vec<EmbeddingBackfillJob>>> {
+    crate::require_admin!(claims);
+
+    let service = SemanticSearchService::new(config.database_pool.clone())?;
+    Ok(Json(service.list_jobs(query.limit.unwrap_or(20)).await?))
+}
@@ -0,0 +1,144 @@
+/// REST API handlers for runtime settings (superadmin)
+
+use axum::{
+    extract::{Path, Query, State},
+    Extension,
+    Json,
+};
+use serde::Deserialize;
+use utoipa::IntoParams;
+use crate::{
+    config::AppConfig,
+    middleware::{error_handling::Result, Claims},
+    models::settings::*,
+    services::{
+        comprehensive_audit_service::{ActionResult, AuditLogEntry, ComprehensiveAuditService, EventCategory, Severity},
+        SettingsService,
+    },
+};
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SettingHistoryQuery {
+    pub limit: Option<i64>,
+}
+
+/// GET /api/admin/settings
+/// Every runtime setting with its effective value and default
+#[utoipa::path(
+    get,
+    path = "/api/admin/settings",
+    tag = "settings",
+    summary = "Every runtime setting with its effective value and default",
+    responses(
+        (status = 200, description = "Success", body = Vec<SettingView>),
+    )
+)]
+pub async fn list_settings(
+    State(config): State<AppConfig>,
+) -> Result<Json<Vec<SettingView>>> {
+    let service = SettingsService::new(config.database_pool.clone());
+    Ok(Json(service.list().await?))
+}
+
+/// PUT /api/admin/settings/:key
+/// Override a setting; every instance applies it within moments, no restart needed
+#[utoipa::path(
+    put,
+    path = "/api/admin/settings/{key}",
+    tag = "settings",
+    summary = "Override a setting; every instance applies it within moments, no restart needed",
+    params(
+        ("key" = String, Path),
+    ),
+    request_body = UpdateSettingRequest,
+    responses(
+        (status = 200, description = "Success", body = SettingView),
+    )
+)]
+pub async fn update_setting(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(key): Path<String>,
+    Json(request): Json<UpdateSettingRequest>,
+) -> Result<Json<SettingView>> {
+    let service = SettingsService::new(config.database_pool.clone());
+    let setting = service.update(&key, &request.value, claims.user_id).await?;
+
+    audit(&config, &claims, &key, "update_setting", &setting).await?;
+    Ok(Json(setting))
+}
+
+/// DELETE /api/admin/settings/:key
+/// Drop the override and go back to the default (environment or built-in)
+#[utoipa::path(
+    delete,
+    path = "/api/admin/settings/{key}",
+    tag = "settings",
+    summary = "Drop the override and go back to the default (environment or built-in)",
+    params(
+        ("key" = String, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = SettingView),
+    )
+)]
+pub async fn reset_setting(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(key): Path<String>,
+) -> Result<Json<SettingView>> {
+    let service = SettingsService::new(config.database_pool.clone());
+    let setting = service.reset(&key, claims.user_id).await?;
+
+    audit(&config, &claims, &key, "reset_setting", &setting).await?;
+    Ok(Json(setting))
+}
+
+/// GET /api/admin/settings/:key/history
+/// Changes of a setting, newest first (`?limit=`, default 50)
+#[utoipa::path(
+    get,
+    path = "/api/admin/settings/{key}/history",
+    tag = "settings",
+    summary = "Changes of a setting, newest first (`?limit=`, default 50)",
+    params(
+        ("key" = String, Path),
+        SettingHistoryQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<SettingChange>),
+    )
+)]
+pub async fn get_setting_history(
+    State(config): State<AppConfig>,
+    Path(key): Path<String>,
+    Query(query): Query<SettingHistoryQuery>,
+) -> Result<Json<Vec<SettingChange>>> {
+    let limit = query.limit.unwrap_or(50).clamp(1, 500);
+    let service = SettingsService::new(config.database_pool.clone());
+    Ok(Json(service.history(&key, limit).await?))
+}
+
+async fn audit(config: &AppConfig, claims: &Claims, key: &str, action: &str, setting: &SettingView) -> Result<()> {
+    ComprehensiveAuditService::new(config.database_pool.clone()).log(AuditLogEntry {
+        event_type: "admin_setting_change".to_string(),
+        event_category: EventCategory::Admin,
+        severity: Severity::Warning,
+        actor_user_id: Some(claims.user_id),
+        actor_type: "user".to_string(),
+        resource_type: Some("setting".to_string()),
+        resource_id: Some(key.to_string()),
+        action: action.to_string(),
+        action_result: ActionResult::Success,
+        event_data: serde_json::json!({
+            "key": key,
+            "value": setting.value,
+            "overridden": setting.overridden,
+        }),
+        compliance_tags: vec!["admin".to_string(), "configuration".to_string()],
+        ..Default::default()
+    }).await?;
+
+    Ok(())
+}
@@ -0,0 +1,143 @@
+/// Sync Source Settings REST API Handlers (admin only)
+///
+/// Runtime rate budgets, API keys and scheduler pause of the catalog sync sources
+/// (`openfda`, `ema`, `mhra`, `health_canada`, `swissmedic`). Changes apply to the
+/// next sync run and the next scheduler tick; no redeploy is needed.
+
+use axum::{
+    extract::{Path, State},
+    Extension, Json,
+};
+use crate::{
+    config::AppConfig,
+    middleware::{error_handling::Result, Claims},
+    models::sync_dashboard::SyncDashboard,
+    models::sync_source::{PauseSchedulerRequest, SyncSourceSettingsResponse, UpdateSyncSourceRequest},
+    services::SyncSourceService,
+};
+
+/// GET /api/admin/sync-sources
+/// Settings of every sync source (API keys are reported as set or not, never returned)
+#[utoipa::path(
+    get,
+    path = "/api/admin/sync-sources",
+    tag = "sync_sources",
+    operation_id = "sync_sources_list_sources",
+    summary = "Settings of every sync source (API keys are reported as set or not, never returned)",
+    responses(
+        (status = 200, description = "Success", body = Vec<SyncSourceSettingsResponse>),
+    )
+)]
+pub async fn list_sources(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Vec<SyncSourceSettingsResponse>>> {
+    crate::require_admin!(claims);
+
+    let service = SyncSourceService::new(config.database_pool.clone());
+    Ok(Json(service.list().await?))
+}
+
+/// PUT /api/admin/sync-sources/:source
+/// Update the rate budget, burst limit or API key of a source
+#[utoipa::path(
+    put,
+    path = "/api/admin/sync-sources/{source}",
+    tag = "sync_sources",
+    summary = "Update the rate budget, burst limit or API key of a source",
+    params(
+        ("source" = String, Path),
+    ),
+    request_body = UpdateSyncSourceRequest,
+    responses(
+        (status = 200, description = "Success", body = SyncSourceSettingsResponse),
+    )
+)]
+pub async fn update_source(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(source): Path<String>,
+    Json(request): Json<UpdateSyncSourceRequest>,
+) -> Result<Json<SyncSourceSettingsResponse>> {
+    crate::require_admin!(claims);
+
+    let service = SyncSourceService::new(config.database_pool.clone());
+    let settings = service.update(&source, request, claims.user_id).await?;
+    Ok(Json(settings))
+}
+
+/// POST /api/admin/sync-sources/:source/pause
+/// Stop scheduled syncs of a source (manual syncs still run)
+#[utoipa::path(
+    post,
+    path = "/api/admin/sync-sources/{source}/pause",
+    tag = "sync_sources",
+    summary = "Stop scheduled syncs of a source (manual syncs still run)",
+    params(
+        ("source" = String, Path),
+    ),
+    request_body = Option<PauseSchedulerRequest>,
+    responses(
+        (status = 200, description = "Success", body = SyncSourceSettingsResponse),
+    )
+)]
+pub async fn pause_scheduler(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(source): Path<String>,
+    request: Option<Json<PauseSchedulerRequest>>,
+) -> Result<Json<SyncSourceSettingsResponse>> {
+    crate::require_admin!(claims);
+
+    let reason = request.and_then(|Json(r)| r.reason);
+    let service = SyncSourceService::new(config.database_pool.clone());
+    let settings = service.set_scheduler_paused(&source, true, reason, claims.user_id).await?;
+    Ok(Json(settings))
+}
+
+/// POST /api/admin/sync-sources/:source/resume
+/// Resume scheduled syncs of a source
+#[utoipa::path(
+    post,
+    path = "/api/admin/sync-sources/{source}/resume",
+    tag = "sync_sources",
+    summary = "Resume scheduled syncs of a source",
+    params(
+        ("source" = String, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = SyncSourceSettingsResponse),
+    )
+)]
+pub async fn resume_scheduler(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(source): Path<String>,
+) -> Result<Json<SyncSourceSettingsResponse>> {
+    crate::require_admin!(claims);
+
+    let service = SyncSourceService::new(config.database_pool.clone());
+    let settings = service.set_scheduler_paused(&source, false, None, claims.user_id).await?;
+    Ok(Json(settings))
+}
+
+/// GET /api/admin/sync-dashboard
+/// Health of every sync pipeline (latest run, failures, staleness, scheduler pause)
+#[utoipa::path(
+    get,
+    path = "/api/admin/sync-dashboard",
+    tag = "sync_sources",
+    summary = "Health of every sync pipeline (latest run, failures, staleness, scheduler pause)",
+    responses(
+        (status = 200, description = "Success", body = SyncDashboard),
+    )
+)]
+pub async fn get_sync_dashboard(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<SyncDashboard>> {
+    crate::require_admin!(claims);
+
+    let service = SyncSourceService::new(config.database_pool.clone());
+    Ok(Json(service.dashboard().await?))
+}
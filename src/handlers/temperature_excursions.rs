@@ -0,0 +1,193 @@
+/// GDP Temperature Excursion REST API Handlers
+///
+/// Seller or buyer attaches temperature logger data (CSV or JSON) to a shipment and gets
+/// an excursion report against the product's storage conditions. The buyer disputes
+/// excursions; the seller accepts or rejects the dispute. The summary feeds GDP
+/// self-inspections.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use uuid::Uuid;
+use validator::Validate;
+use crate::{
+    config::AppConfig,
+    middleware::{error_handling::{AppError, Result}, Claims},
+    models::temperature_excursion::*,
+    services::TemperatureExcursionService,
+};
+
+/// GET /api/marketplace/transactions/:id/temperature-logs
+#[utoipa::path(
+    get,
+    path = "/api/marketplace/transactions/{id}/temperature-logs",
+    tag = "temperature_excursions",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<TemperatureLog>),
+    )
+)]
+pub async fn list_temperature_logs(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(transaction_id): Path<Uuid>,
+) -> Result<Json<Vec<TemperatureLog>>> {
+    let service = TemperatureExcursionService::new(config.database_pool.clone());
+    Ok(Json(service.list_logs(transaction_id, claims.user_id).await?))
+}
+
+/// POST /api/marketplace/transactions/:id/temperature-logs
+/// Attach a logger export; returns the excursion report
+#[utoipa::path(
+    post,
+    path = "/api/marketplace/transactions/{id}/temperature-logs",
+    tag = "temperature_excursions",
+    summary = "Attach a logger export; returns the excursion report",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    request_body = UploadTemperatureLogRequest,
+    responses(
+        (status = 201, description = "Created", body = ExcursionReport),
+    )
+)]
+pub async fn upload_temperature_log(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(transaction_id): Path<Uuid>,
+    Json(request): Json<UploadTemperatureLogRequest>,
+) -> Result<(StatusCode, Json<ExcursionReport>)> {
+    request.validate().map_err(AppError::Validation)?;
+
+    let service = TemperatureExcursionService::new(config.database_pool.clone());
+    let report = service.upload(transaction_id, claims.user_id, request).await?;
+    Ok((StatusCode::CREATED, Json(report)))
+}
+
+/// GET /api/marketplace/transactions/:id/temperature-logs/:log_id
+/// Excursion report of a temperature log
+#[utoipa::path(
+    get,
+    path = "/api/marketplace/transactions/{id}/temperature-logs/{log_id}",
+    tag = "temperature_excursions",
+    summary = "Excursion report of a temperature log",
+    params(
+        ("id" = Uuid, Path),
+        ("log_id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = ExcursionReport),
+    )
+)]
+pub async fn get_excursion_report(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path((transaction_id, log_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<ExcursionReport>> {
+    let service = TemperatureExcursionService::new(config.database_pool.clone());
+    Ok(Json(service.report(transaction_id, log_id, claims.user_id).await?))
+}
+
+/// POST /api/marketplace/transactions/:id/temperature-logs/:log_id/dispute
+/// Dispute the excursions of a temperature log (buyer only)
+#[utoipa::path(
+    post,
+    path = "/api/marketplace/transactions/{id}/temperature-logs/{log_id}/dispute",
+    tag = "temperature_excursions",
+    summary = "Dispute the excursions of a temperature log (buyer only)",
+    params(
+        ("id" = Uuid, Path),
+        ("log_id" = Uuid, Path),
+    ),
+    request_body = OpenDisputeRequest,
+    responses(
+        (status = 201, description = "Created", body = TransactionDispute),
+    )
+)]
+pub async fn open_excursion_dispute(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path((transaction_id, log_id)): Path<(Uuid, Uuid)>,
+    Json(request): Json<OpenDisputeRequest>,
+) -> Result<(StatusCode, Json<TransactionDispute>)> {
+    request.validate().map_err(AppError::Validation)?;
+
+    let service = TemperatureExcursionService::new(config.database_pool.clone());
+    let dispute = service.open_dispute(transaction_id, log_id, claims.user_id, request).await?;
+    Ok((StatusCode::CREATED, Json(dispute)))
+}
+
+/// GET /api/marketplace/transactions/:id/disputes
+#[utoipa::path(
+    get,
+    path = "/api/marketplace/transactions/{id}/disputes",
+    tag = "temperature_excursions",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<TransactionDispute>),
+    )
+)]
+pub async fn list_transaction_disputes(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(transaction_id): Path<Uuid>,
+) -> Result<Json<Vec<TransactionDispute>>> {
+    let service = TemperatureExcursionService::new(config.database_pool.clone());
+    Ok(Json(service.list_disputes(transaction_id, claims.user_id).await?))
+}
+
+/// POST /api/marketplace/disputes/:id/resolve
+/// Seller accepts or rejects; buyer withdraws
+#[utoipa::path(
+    post,
+    path = "/api/marketplace/disputes/{id}/resolve",
+    tag = "temperature_excursions",
+    summary = "Seller accepts or rejects; buyer withdraws",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    request_body = ResolveDisputeRequest,
+    responses(
+        (status = 200, description = "Success", body = TransactionDispute),
+    )
+)]
+pub async fn resolve_dispute(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(dispute_id): Path<Uuid>,
+    Json(request): Json<ResolveDisputeRequest>,
+) -> Result<Json<TransactionDispute>> {
+    request.validate().map_err(AppError::Validation)?;
+
+    let service = TemperatureExcursionService::new(config.database_pool.clone());
+    Ok(Json(service.resolve_dispute(dispute_id, claims.user_id, request).await?))
+}
+
+/// GET /api/marketplace/temperature-excursions/summary
+/// GDP self-inspection figures of the user's shipments (`?from=&to=`, default last year)
+#[utoipa::path(
+    get,
+    path = "/api/marketplace/temperature-excursions/summary",
+    tag = "temperature_excursions",
+    summary = "GDP self-inspection figures of the user's shipments (`?from=&to=`, default last year)",
+    params(
+        TemperatureSummaryQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = TemperatureSummary),
+    )
+)]
+pub async fn get_temperature_summary(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<TemperatureSummaryQuery>,
+) -> Result<Json<TemperatureSummary>> {
+    let service = TemperatureExcursionService::new(config.database_pool.clone());
+    Ok(Json(service.summary(claims.user_id, query).await?))
+}
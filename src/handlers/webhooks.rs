@@ -0,0 +1,229 @@
+/// Outbound Webhook REST API Handlers
+///
+/// Register HTTPS endpoints for platform events, inspect the delivery log and replay
+/// deliveries. Payloads are signed with the endpoint secret
+/// (`X-Webhook-Signature: sha256=<hex>` over the raw body).
+
+use axum::{
+    extract::{Path, Query, State},
+    Extension, Json,
+};
+use uuid::Uuid;
+use crate::{
+    config::AppConfig,
+    middleware::{error_handling::Result, Claims},
+    models::webhook::*,
+    services::OutboundWebhookService,
+};
+
+/// GET /api/webhooks/event-types
+#[utoipa::path(
+    get,
+    path = "/api/webhooks/event-types",
+    tag = "webhooks",
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
+pub async fn list_event_types(
+    Extension(_claims): Extension<Claims>,
+) -> Result<Json<serde_json::Value>> {
+    Ok(Json(serde_json::json!(
+        WebhookEventType::ALL
+            .iter()
+            .map(|t| serde_json::json!({ "event_type": t.as_str(), "description": t.description() }))
+            .collect::<Vec<_>>()
+    )))
+}
+
+/// GET /api/webhooks/endpoints
+#[utoipa::path(
+    get,
+    path = "/api/webhooks/endpoints",
+    tag = "webhooks",
+    responses(
+        (status = 200, description = "Success", body = Vec<WebhookEndpoint>),
+    )
+)]
+pub async fn list_endpoints(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+) -> Result<Json<Vec<WebhookEndpoint>>> {
+    let service = OutboundWebhookService::new(config.database_pool.clone());
+    Ok(Json(service.list_endpoints(claims.user_id).await?))
+}
+
+/// POST /api/webhooks/endpoints
+/// The signing secret is only returned here and on rotation
+#[utoipa::path(
+    post,
+    path = "/api/webhooks/endpoints",
+    tag = "webhooks",
+    summary = "The signing secret is only returned here and on rotation",
+    request_body = CreateWebhookEndpointRequest,
+    responses(
+        (status = 200, description = "Success", body = WebhookEndpointWithSecret),
+    )
+)]
+pub async fn create_endpoint(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<CreateWebhookEndpointRequest>,
+) -> Result<Json<WebhookEndpointWithSecret>> {
+    let service = OutboundWebhookService::new(config.database_pool.clone());
+    Ok(Json(service.create_endpoint(claims.user_id, request).await?))
+}
+
+/// GET /api/webhooks/endpoints/:id
+#[utoipa::path(
+    get,
+    path = "/api/webhooks/endpoints/{id}",
+    tag = "webhooks",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = WebhookEndpoint),
+    )
+)]
+pub async fn get_endpoint(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(endpoint_id): Path<Uuid>,
+) -> Result<Json<WebhookEndpoint>> {
+    let service = OutboundWebhookService::new(config.database_pool.clone());
+    Ok(Json(service.get_endpoint(endpoint_id, claims.user_id).await?))
+}
+
+/// PUT /api/webhooks/endpoints/:id
+#[utoipa::path(
+    put,
+    path = "/api/webhooks/endpoints/{id}",
+    tag = "webhooks",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    request_body = UpdateWebhookEndpointRequest,
+    responses(
+        (status = 200, description = "Success", body = WebhookEndpoint),
+    )
+)]
+pub async fn update_endpoint(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(endpoint_id): Path<Uuid>,
+    Json(request): Json<UpdateWebhookEndpointRequest>,
+) -> Result<Json<WebhookEndpoint>> {
+    let service = OutboundWebhookService::new(config.database_pool.clone());
+    Ok(Json(service.update_endpoint(endpoint_id, claims.user_id, request).await?))
+}
+
+/// DELETE /api/webhooks/endpoints/:id
+#[utoipa::path(
+    delete,
+    path = "/api/webhooks/endpoints/{id}",
+    tag = "webhooks",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = serde_json::Value),
+    )
+)]
+pub async fn delete_endpoint(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(endpoint_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>> {
+    let service = OutboundWebhookService::new(config.database_pool.clone());
+    service.delete_endpoint(endpoint_id, claims.user_id).await?;
+    Ok(Json(serde_json::json!({ "deleted": true })))
+}
+
+/// POST /api/webhooks/endpoints/:id/rotate-secret
+#[utoipa::path(
+    post,
+    path = "/api/webhooks/endpoints/{id}/rotate-secret",
+    tag = "webhooks",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = WebhookEndpointWithSecret),
+    )
+)]
+pub async fn rotate_endpoint_secret(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(endpoint_id): Path<Uuid>,
+) -> Result<Json<WebhookEndpointWithSecret>> {
+    let service = OutboundWebhookService::new(config.database_pool.clone());
+    Ok(Json(service.rotate_secret(endpoint_id, claims.user_id).await?))
+}
+
+/// GET /api/webhooks/deliveries
+/// Delivery log, newest first (filter by endpoint_id, status, event_type)
+#[utoipa::path(
+    get,
+    path = "/api/webhooks/deliveries",
+    tag = "webhooks",
+    summary = "Delivery log, newest first (filter by endpoint_id, status, event_type)",
+    params(
+        WebhookDeliveriesQuery,
+    ),
+    responses(
+        (status = 200, description = "Success", body = Vec<WebhookDelivery>),
+    )
+)]
+pub async fn list_deliveries(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<WebhookDeliveriesQuery>,
+) -> Result<Json<Vec<WebhookDelivery>>> {
+    let service = OutboundWebhookService::new(config.database_pool.clone());
+    Ok(Json(service.list_deliveries(claims.user_id, &query).await?))
+}
+
+/// GET /api/webhooks/deliveries/:id
+#[utoipa::path(
+    get,
+    path = "/api/webhooks/deliveries/{id}",
+    tag = "webhooks",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = WebhookDelivery),
+    )
+)]
+pub async fn get_delivery(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(delivery_id): Path<Uuid>,
+) -> Result<Json<WebhookDelivery>> {
+    let service = OutboundWebhookService::new(config.database_pool.clone());
+    Ok(Json(service.get_delivery(delivery_id, claims.user_id).await?))
+}
+
+/// POST /api/webhooks/deliveries/:id/replay
+/// Send a delivered or failed event again (same event id, new delivery)
+#[utoipa::path(
+    post,
+    path = "/api/webhooks/deliveries/{id}/replay",
+    tag = "webhooks",
+    summary = "Send a delivered or failed event again (same event id, new delivery)",
+    params(
+        ("id" = Uuid, Path),
+    ),
+    responses(
+        (status = 200, description = "Success", body = WebhookDelivery),
+    )
+)]
+pub async fn replay_delivery(
+    State(config): State<AppConfig>,
+    Extension(claims): Extension<Claims>,
+    Path(delivery_id): Path<Uuid>,
+) -> Result<Json<WebhookDelivery>> {
+    let service = OutboundWebhookService::new(config.database_pool.clone());
+    Ok(Json(service.replay_delivery(delivery_id, claims.user_id).await?))
+}
@@ -1,9 +1,11 @@
 pub mod config;
+pub mod graphql;
 pub mod models;
 pub mod repositories;
 pub mod services;
 pub mod handlers;
 pub mod middleware;
+pub mod openapi;
 pub mod utils;
 
 use std::net::SocketAddr;
@@ -125,7 +127,7 @@ fn create_app(config: AppConfig) -> Router {
                         if let Some(auth_header) = auth_header {
                             if let Some(token) = crate::middleware::JwtService::extract_token_from_header(auth_header) {
                                 let jwt_service = crate::middleware::JwtService::new(&state.jwt_secret);
-                                if let Ok(claims) = jwt_service.validate_token(token) {
+                                if let Some(claims) = jwt_service.validate_token(token).ok().filter(|claims| claims.allows_path(req.uri().path())) {
                                     let mut req = req;
                                     req.extensions_mut().insert(claims);
                                     return Ok::<axum::response::Response, crate::middleware::error_handling::AppError>(next.run(req).await);
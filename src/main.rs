@@ -6,12 +6,14 @@ use axum::{
     middleware::Next,
 };
 use tower::ServiceBuilder;
-use tower_http::cors::{CorsLayer, Any};
-use axum::http::{HeaderValue, Method, header};
+use tower_http::cors::{AllowOrigin, CorsLayer, Any};
+use tower_http::catch_panic::CatchPanicLayer;
+use axum::http::{HeaderName, HeaderValue, Method, header};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 use atlas_pharma::config::AppConfig;
-use atlas_pharma::middleware::ip_rate_limiter::{RateLimiter, RateLimitConfig};
+use atlas_pharma::middleware::ip_rate_limiter::RateLimiter;
+use atlas_pharma::services::{RuntimeSettings, Shutdown};
 use std::sync::Arc;
 use atlas_pharma::handlers::{
     auth::{register, login, logout, get_profile, update_profile, delete_account, refresh_token},
@@ -52,37 +54,76 @@ use atlas_pharma::handlers::{
     ai_import::{
         upload_and_analyze, list_sessions, get_session,
         start_import, get_session_rows, get_user_quota,
+        get_extraction, review_extraction, apply_extraction,
+        list_remembered_mappings, delete_remembered_mapping,
+        preview_rows, update_row_decisions, rollback_import,
     },
     nl_query,
     inquiry_assistant,
     alerts,
 };
-use atlas_pharma::middleware::auth_middleware;
+use atlas_pharma::middleware::{auth_middleware, create_optional_auth_middleware};
 
 pub fn create_app(config: AppConfig) -> Router {
     // 🔒 PRODUCTION LOGGING CONFIGURATION
     // Default to INFO level (not DEBUG) to prevent verbose logging in production
     // Override with RUST_LOG environment variable for debugging
-    tracing_subscriber::registry()
-        .with(tracing_subscriber::EnvFilter::new(
-            std::env::var("RUST_LOG").unwrap_or_else(|_| "atlas_pharma=info,tower_http=info,sqlx=warn".into()),
-        ))
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+    // JSON lines by default (LOG_FORMAT=pretty for local development); every line
+    // passes through the log sanitizer's PII redaction before it is written
+    let log_filter = tracing_subscriber::EnvFilter::new(
+        std::env::var("RUST_LOG").unwrap_or_else(|_| "atlas_pharma=info,tower_http=info,sqlx=warn".into()),
+    );
+    let log_writer = atlas_pharma::utils::log_sanitizer::RedactingMakeWriter;
+    if std::env::var("LOG_FORMAT").map(|format| format == "pretty").unwrap_or(false) {
+        tracing_subscriber::registry()
+            .with(log_filter)
+            .with(tracing_subscriber::fmt::layer().with_writer(log_writer))
+            .init();
+    } else {
+        tracing_subscriber::registry()
+            .with(log_filter)
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .json()
+                    .flatten_event(true)
+                    .with_current_span(true)
+                    .with_span_list(false)
+                    .with_writer(log_writer),
+            )
+            .init();
+    }
 
     // 🔒 PRODUCTION RATE LIMITING
-    let auth_rate_limiter = Arc::new(RateLimiter::new(RateLimitConfig::auth()));
-    let api_rate_limiter = Arc::new(RateLimiter::new(RateLimitConfig::api()));
+    // Limits are runtime settings (loaded before the app is built, re-applied on change)
+    let auth_rate_limiter = RateLimiter::named("auth", RuntimeSettings::auth_rate_limit());
+    let api_rate_limiter = RateLimiter::named("api", RuntimeSettings::api_rate_limit());
 
     // 🔒 PRODUCTION TOKEN BLACKLIST (logout/revocation)
     let token_blacklist = Arc::new(atlas_pharma::services::TokenBlacklistService::new());
 
+    // ⛔ SUSPENSIONS: lift timed suspensions and sync session revocations from other instances
+    let suspension_scheduler = atlas_pharma::services::UserSuspensionScheduler::new(
+        config.database_pool.clone(),
+        token_blacklist.clone(),
+    );
+    // Session revocations from any instance arrive over LISTEN/NOTIFY; the scheduler's sync is the fallback
+    tokio::spawn(suspension_scheduler.clone().run_listener());
+    tokio::spawn(async move {
+        tracing::info!("⛔ User suspension scheduler initialized");
+        suspension_scheduler.run().await;
+    });
+
+    // 📡 REAL-TIME NOTIFICATIONS (SSE fan-out, fed by Postgres LISTEN/NOTIFY)
+    let realtime_hub = atlas_pharma::services::RealtimeHub::new();
+
     // 📋 PRODUCTION AUDIT LOGGING (compliance: SOC 2, HIPAA, ISO 27001)
     let audit_service = Arc::new(atlas_pharma::services::ComprehensiveAuditService::new(config.database_pool.clone()));
 
     // 🔒 SECURITY: Strict CORS policy - only allow whitelisted origins
+    // Origins are a runtime setting (`cors.allowed_origins`, default CORS_ORIGINS)
+    let allowed_origins = RuntimeSettings::cors_origins();
     // Validate CORS origins for security issues
-    for origin in &config.cors_origins {
+    for origin in &allowed_origins {
         // ⚠️  WARNING: Check for insecure origin patterns
         if origin.starts_with("http://") && !origin.contains("localhost") {
             tracing::warn!(
@@ -104,24 +145,12 @@ pub fn create_app(config: AppConfig) -> Router {
         }
     }
 
-    let cors_origins: Vec<HeaderValue> = config
-        .cors_origins
-        .iter()
-        .filter_map(|origin| {
-            match origin.parse() {
-                Ok(header_val) => Some(header_val),
-                Err(e) => {
-                    tracing::error!("❌ Invalid CORS origin '{}': {}", origin, e);
-                    None
-                }
-            }
-        })
-        .collect();
-
-    tracing::info!("✅ CORS configured with {} allowed origins", cors_origins.len());
+    tracing::info!("✅ CORS configured with {} allowed origins", allowed_origins.len());
 
     let cors = CorsLayer::new()
-        .allow_origin(cors_origins)
+        .allow_origin(AllowOrigin::predicate(|origin: &HeaderValue, _| {
+            RuntimeSettings::is_cors_origin_allowed(origin.as_bytes())
+        }))
         .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE, Method::OPTIONS])
         .allow_credentials(true)  // Required for httpOnly cookies
         .allow_headers([
@@ -129,6 +158,12 @@ pub fn create_app(config: AppConfig) -> Router {
             header::CONTENT_TYPE,
             header::ACCEPT,
             header::COOKIE,
+        ])
+        .expose_headers([
+            header::RETRY_AFTER,
+            HeaderName::from_static(atlas_pharma::middleware::ip_rate_limiter::RATE_LIMIT_LIMIT_HEADER),
+            HeaderName::from_static(atlas_pharma::middleware::ip_rate_limiter::RATE_LIMIT_REMAINING_HEADER),
+            HeaderName::from_static(atlas_pharma::middleware::ip_rate_limiter::RATE_LIMIT_RESET_HEADER),
         ]);
 
     let app = Router::new()
@@ -146,6 +181,7 @@ pub fn create_app(config: AppConfig) -> Router {
                     Router::new()
                         .route("/logout", post(logout))
                         .route("/profile", get(get_profile))
+                        .route("/limits", get(atlas_pharma::handlers::auth::get_limits))  // 🔒 Rate-limit budgets and AI quotas of the caller
                         .route("/profile", put(update_profile))
                         .route("/change-password", post(atlas_pharma::handlers::auth::change_password))  // 🔒 SECURITY: Password change with session invalidation
                         .route("/delete", delete(delete_account))
@@ -169,8 +205,10 @@ pub fn create_app(config: AppConfig) -> Router {
         .nest(
             "/api/admin",
             Router::new()
-                // Admin health check (public - for monitoring)
+                // Admin health checks (public - for monitoring)
                 .route("/health", get(atlas_pharma::handlers::admin::health_check))
+                .route("/health/ready", get(atlas_pharma::handlers::admin::health_check))
+                .route("/health/live", get(atlas_pharma::handlers::admin::liveness_check))
                 // Admin-only endpoints (require admin or superadmin role)
                 .merge(
                     Router::new()
@@ -178,18 +216,91 @@ pub fn create_app(config: AppConfig) -> Router {
                         .route("/users", get(atlas_pharma::handlers::admin::list_users))
                         .route("/users/:id", get(atlas_pharma::handlers::admin::get_user))
                         .route("/users/:id/verify", post(atlas_pharma::handlers::admin::verify_user))
+                        // Suspensions and listing takedowns
+                        .route("/users/:id/suspend", post(atlas_pharma::handlers::admin::suspend_user))
+                        .route("/users/:id/reinstate", post(atlas_pharma::handlers::admin::reinstate_user))
+                        .route("/users/:id/suspensions", get(atlas_pharma::handlers::admin::get_user_suspensions))
+                        .route("/listings/takedown", post(atlas_pharma::handlers::admin::take_down_listings))
+                        .route("/listings/restore", post(atlas_pharma::handlers::admin::restore_listings))
                         // Verification queue
                         .route("/verification-queue", get(atlas_pharma::handlers::admin::get_verification_queue))
+                        .route("/verification-queue/decisions", post(atlas_pharma::handlers::admin::decide_verifications))
                         // Statistics
                         .route("/stats", get(atlas_pharma::handlers::admin::get_admin_stats))
+                        // Analytics (funnels, active users, GMV; ?format=csv to export)
+                        .route("/analytics/funnel", get(atlas_pharma::handlers::admin_analytics::get_funnel))
+                        .route("/analytics/active-users", get(atlas_pharma::handlers::admin_analytics::get_active_users))
+                        .route("/analytics/gmv", get(atlas_pharma::handlers::admin_analytics::get_gmv))
+                        // Middleware protecting each route (built at startup)
+                        .route("/route-policies", get(atlas_pharma::handlers::admin::get_route_policies))
                         // Audit logs
                         .route("/audit-logs", get(atlas_pharma::handlers::admin::get_audit_logs))
+                        .route("/audit-logs/chain/verify", get(atlas_pharma::handlers::audit_chain::verify_chain))
+                        .route("/audit-logs/chain/anchors", get(atlas_pharma::handlers::audit_chain::list_anchors).post(atlas_pharma::handlers::audit_chain::create_anchor))
+                        // Background jobs
+                        .route("/jobs", get(atlas_pharma::handlers::background_jobs::list_jobs))
+                        .route("/jobs/:id", get(atlas_pharma::handlers::background_jobs::get_job))
+                        .route("/jobs/:id/retry", post(atlas_pharma::handlers::background_jobs::retry_job))
+                        .route("/jobs/:id/cancel", post(atlas_pharma::handlers::background_jobs::cancel_job))
+                        // Operations dashboard
+                        .route("/ops", get(atlas_pharma::handlers::admin_ops::get_ops_dashboard))
                         // Security monitoring (read-only)
                         .route("/security/api-usage", get(atlas_pharma::handlers::admin_security::get_api_usage_analytics))
                         .route("/security/quotas", get(atlas_pharma::handlers::admin_security::get_user_quotas))
                         .route("/security/encryption", get(atlas_pharma::handlers::admin_security::get_encryption_status))
+                        .route("/security/encryption/reencryption", get(atlas_pharma::handlers::admin_security::get_reencryption_status))
                         .route("/security/metrics", get(atlas_pharma::handlers::admin_security::get_metrics_summary))
                         .route("/security/rate-limits", get(atlas_pharma::handlers::admin_security::get_rate_limit_status))
+                        // Catalog sync sources (rate budgets, API keys, scheduler pause)
+                        .route("/sync-dashboard", get(atlas_pharma::handlers::sync_sources::get_sync_dashboard))
+                        .route("/sync-sources", get(atlas_pharma::handlers::sync_sources::list_sources))
+                        .route("/sync-sources/:source", put(atlas_pharma::handlers::sync_sources::update_source))
+                        .route("/sync-sources/:source/pause", post(atlas_pharma::handlers::sync_sources::pause_scheduler))
+                        .route("/sync-sources/:source/resume", post(atlas_pharma::handlers::sync_sources::resume_scheduler))
+                        // Semantic search embeddings (backfill jobs)
+                        .route("/search/embeddings/backfill", post(atlas_pharma::handlers::semantic_search::start_embedding_backfill))
+                        .route("/search/embeddings/jobs", get(atlas_pharma::handlers::semantic_search::list_embedding_jobs))
+                        // AI response cache (hit metrics, purge)
+                        .route("/ai-cache/stats", get(atlas_pharma::handlers::admin::get_ai_cache_stats))
+                        .route("/ai-cache/purge", post(atlas_pharma::handlers::admin::purge_ai_cache))
+                        .route("/query-cache/stats", get(atlas_pharma::handlers::admin::get_query_cache_stats))
+                        .route("/query-cache/purge", post(atlas_pharma::handlers::admin::purge_query_cache))
+                        .route("/search-engine/status", get(atlas_pharma::handlers::admin::get_search_engine_status))
+                        .route("/search-engine/reindex", post(atlas_pharma::handlers::admin::reindex_search_engine))
+                        // AI quota (per-user breakdown)
+                        .route("/ai-quota/:user_id", get(atlas_pharma::handlers::ai_quota::admin_get_usage))
+                        // Billing (per-account metered usage)
+                        .route("/billing/accounts/:user_id/usage", get(atlas_pharma::handlers::billing::admin_get_usage))
+                        // Marketplace anomalies (review workflow)
+                        .route("/anomalies", get(atlas_pharma::handlers::anomalies::list_anomalies))
+                        .route("/anomalies/run", post(atlas_pharma::handlers::anomalies::run_anomaly_detection))
+                        .route("/anomalies/:id", get(atlas_pharma::handlers::anomalies::get_anomaly))
+                        .route("/anomalies/:id/confirm", post(atlas_pharma::handlers::anomalies::confirm_anomaly))
+                        .route("/anomalies/:id/dismiss", post(atlas_pharma::handlers::anomalies::dismiss_anomaly))
+                        // Platform announcements
+                        .route("/announcements", get(atlas_pharma::handlers::announcements::list_announcements))
+                        .route("/announcements", post(atlas_pharma::handlers::announcements::create_announcement))
+                        .route("/announcements/:id/withdraw", post(atlas_pharma::handlers::announcements::withdraw_announcement))
+                        // Legal holds (block purges and deletion of held data)
+                        .route("/legal-holds", get(atlas_pharma::handlers::legal_holds::list_legal_holds))
+                        .route("/legal-holds", post(atlas_pharma::handlers::legal_holds::create_legal_hold))
+                        .route("/legal-holds/:id", get(atlas_pharma::handlers::legal_holds::get_legal_hold))
+                        .route("/legal-holds/:id/release", post(atlas_pharma::handlers::legal_holds::release_legal_hold))
+                        // License registry (verification, expiry overview)
+                        .route("/licenses", get(atlas_pharma::handlers::licenses::list_licenses))
+                        .route("/licenses/:id/review", post(atlas_pharma::handlers::licenses::review_license))
+                        // Sanctions screening (review queue, list sync)
+                        .route("/sanctions/screenings", get(atlas_pharma::handlers::sanctions::list_screenings))
+                        .route("/sanctions/screenings/:id", get(atlas_pharma::handlers::sanctions::get_screening))
+                        .route("/sanctions/screenings/:id/review", post(atlas_pharma::handlers::sanctions::review_screening))
+                        .route("/sanctions/users/:id/screen", post(atlas_pharma::handlers::sanctions::screen_user))
+                        .route("/sanctions/lists", get(atlas_pharma::handlers::sanctions::get_list_status))
+                        .route("/sanctions/lists/syncs", get(atlas_pharma::handlers::sanctions::list_syncs))
+                        .route("/sanctions/lists/sync", post(atlas_pharma::handlers::sanctions::sync_lists))
+                        // Regulatory rules engine (jurisdiction requirements of trades)
+                        .route("/regulatory-rules", get(atlas_pharma::handlers::regulatory_rules::list_rules).post(atlas_pharma::handlers::regulatory_rules::create_rule))
+                        .route("/regulatory-rules/evaluations", get(atlas_pharma::handlers::regulatory_rules::list_evaluations))
+                        .route("/regulatory-rules/:id", get(atlas_pharma::handlers::regulatory_rules::get_rule).put(atlas_pharma::handlers::regulatory_rules::update_rule).delete(atlas_pharma::handlers::regulatory_rules::delete_rule))
                         .layer(middleware::from_fn_with_state(config.clone(), auth_middleware))
                         .layer(middleware::from_fn(atlas_pharma::middleware::admin_middleware))
                 )
@@ -201,6 +312,34 @@ pub fn create_app(config: AppConfig) -> Router {
                         // Security management (write operations)
                         .route("/security/quotas/:user_id", put(atlas_pharma::handlers::admin_security::update_user_quota))
                         .route("/security/encryption/rotate", post(atlas_pharma::handlers::admin_security::rotate_encryption_key))
+                        .route("/security/encryption/reencrypt", post(atlas_pharma::handlers::admin_security::start_reencryption))
+                        // AI plan tiers and per-feature overrides
+                        .route("/ai-quota/:user_id/tier", put(atlas_pharma::handlers::ai_quota::admin_set_tier))
+                        .route("/ai-quota/:user_id/overrides/:feature", put(atlas_pharma::handlers::ai_quota::admin_set_override))
+                        .route("/ai-quota/:user_id/overrides/:feature", delete(atlas_pharma::handlers::ai_quota::admin_remove_override))
+                        // Billing plans and monthly statements
+                        .route("/billing/accounts/:user_id/plan", put(atlas_pharma::handlers::billing::admin_assign_plan))
+                        .route("/billing/statements/generate", post(atlas_pharma::handlers::billing::admin_generate_statements))
+                        // Runtime settings (rate limits, sync intervals, quota defaults, CORS)
+                        .route("/settings", get(atlas_pharma::handlers::settings::list_settings))
+                        .route("/settings/:key", put(atlas_pharma::handlers::settings::update_setting))
+                        .route("/settings/:key", delete(atlas_pharma::handlers::settings::reset_setting))
+                        .route("/settings/:key/history", get(atlas_pharma::handlers::settings::get_setting_history))
+                        // Maintenance mode and drain status
+                        .route("/maintenance", get(atlas_pharma::handlers::maintenance::get_maintenance_status))
+                        .route("/maintenance", put(atlas_pharma::handlers::maintenance::update_maintenance))
+                        // Payload capture for investigations (rules, redacted captures; views audited)
+                        .route("/payload-captures", get(atlas_pharma::handlers::payload_captures::list_captures))
+                        .route("/payload-captures/:id", get(atlas_pharma::handlers::payload_captures::get_capture))
+                        .route("/payload-captures/rules", get(atlas_pharma::handlers::payload_captures::list_capture_rules))
+                        .route("/payload-captures/rules", post(atlas_pharma::handlers::payload_captures::create_capture_rule))
+                        .route("/payload-captures/rules/:id", delete(atlas_pharma::handlers::payload_captures::revoke_capture_rule))
+                        // Data retention (policies, purge runs)
+                        .route("/retention/policies", get(atlas_pharma::handlers::data_retention::list_policies))
+                        .route("/retention/policies/:category", put(atlas_pharma::handlers::data_retention::update_policy))
+                        .route("/retention/runs", get(atlas_pharma::handlers::data_retention::list_purge_runs))
+                        .route("/retention/runs", post(atlas_pharma::handlers::data_retention::start_purge_run))
+                        .route("/retention/runs/:id", get(atlas_pharma::handlers::data_retention::get_purge_run))
                         .layer(middleware::from_fn_with_state(config.clone(), auth_middleware))
                         .layer(middleware::from_fn(atlas_pharma::middleware::superadmin_middleware))
                 )
@@ -225,7 +364,24 @@ pub fn create_app(config: AppConfig) -> Router {
                 .route("/search", get(search_pharmaceuticals))
                 .route("/manufacturers", get(get_manufacturers))
                 .route("/categories", get(get_categories))
+                .route("/:id/ingredients", get(atlas_pharma::handlers::pharmaceutical::get_ingredients))
+                .route("/:id/ingredients", put(atlas_pharma::handlers::pharmaceutical::set_ingredients))
+                // Product images
+                .route("/:id/images", post(atlas_pharma::handlers::product_images::upload_image))
+                .route("/:id/images/:image_id", delete(atlas_pharma::handlers::product_images::delete_image))
+                .route("/:id/images/:image_id/moderate", put(atlas_pharma::handlers::product_images::moderate_image))
+                .route("/:id/images/:image_id/primary", put(atlas_pharma::handlers::product_images::set_primary_image))
+                .route("/images/moderation-queue", get(atlas_pharma::handlers::product_images::get_moderation_queue))
                 .layer(middleware::from_fn_with_state(config.clone(), auth_middleware))
+                // Product image delivery (public - approved images are CDN-cacheable)
+                .merge(
+                    Router::new()
+                        .route("/:id/images", get(atlas_pharma::handlers::product_images::list_images))
+                        .route("/:id/images/:image_id/file", get(atlas_pharma::handlers::product_images::get_image_file))
+                        .route("/:id/images/:image_id/thumbnail", get(atlas_pharma::handlers::product_images::get_image_thumbnail))
+                        // Signed-in uploaders and admins also see unmoderated images
+                        .layer(middleware::from_fn_with_state(config.clone(), create_optional_auth_middleware()))
+                )
         )
         .nest(
             "/api/inventory",
@@ -249,11 +405,27 @@ pub fn create_app(config: AppConfig) -> Router {
                 .route("/inquiries/:id/messages", get(get_inquiry_messages))
                 .route("/inquiries/:id/messages", post(create_message))
                 .route("/inquiries/:id/messages/count", get(get_message_count))
+                .route("/inquiries/:id/regulatory-requirements", get(atlas_pharma::handlers::regulatory_rules::get_inquiry_requirements))
+                .route("/regulatory-check", post(atlas_pharma::handlers::regulatory_rules::check_requirements))
                 .route("/transactions", post(create_transaction))
                 .route("/transactions/:id", get(get_transaction))
                 .route("/transactions/my", get(get_user_transactions))
                 .route("/transactions/:id/complete", post(complete_transaction))
                 .route("/transactions/:id/cancel", post(cancel_transaction))
+                .route("/transactions/:id/temperature-logs", get(atlas_pharma::handlers::temperature_excursions::list_temperature_logs).post(atlas_pharma::handlers::temperature_excursions::upload_temperature_log))
+                .route("/transactions/:id/temperature-logs/:log_id", get(atlas_pharma::handlers::temperature_excursions::get_excursion_report))
+                .route("/transactions/:id/temperature-logs/:log_id/dispute", post(atlas_pharma::handlers::temperature_excursions::open_excursion_dispute))
+                .route("/transactions/:id/disputes", get(atlas_pharma::handlers::temperature_excursions::list_transaction_disputes))
+                .route("/disputes/:id/resolve", post(atlas_pharma::handlers::temperature_excursions::resolve_dispute))
+                .route("/temperature-excursions/summary", get(atlas_pharma::handlers::temperature_excursions::get_temperature_summary))
+                .layer(middleware::from_fn_with_state(config.clone(), auth_middleware))
+        )
+        .nest(
+            "/api/licenses",
+            Router::new()
+                .route("/", get(atlas_pharma::handlers::licenses::get_license_registry).post(atlas_pharma::handlers::licenses::upload_license))
+                .route("/:id", get(atlas_pharma::handlers::licenses::get_license).delete(atlas_pharma::handlers::licenses::delete_license))
+                .route("/:id/document", get(atlas_pharma::handlers::licenses::get_license_document))
                 .layer(middleware::from_fn_with_state(config.clone(), auth_middleware))
         )
         .nest(
@@ -261,6 +433,7 @@ pub fn create_app(config: AppConfig) -> Router {
             Router::new()
                 .route("/inventory/search", get(search_marketplace))
                 .route("/expiry-alerts", get(get_expiry_alerts))
+                .layer(middleware::from_fn_with_state(config.clone(), atlas_pharma::middleware::query_budget_middleware))  // ⏱️  Per-request time budget (PUBLIC_QUERY_BUDGET_MS)
         )
         .nest(
             "/api/openfda",
@@ -268,16 +441,28 @@ pub fn create_app(config: AppConfig) -> Router {
                 // Public endpoints
                 .route("/search", get(search_catalog))
                 .route("/ndc/:ndc", get(get_by_ndc))
+                .route("/ndc/:ndc/history", get(atlas_pharma::handlers::openfda::get_ndc_history))
                 .route("/stats", get(get_stats))
+                .route("/export", get(atlas_pharma::handlers::openfda::export_catalog))
                 .route("/manufacturers", get(get_openfda_manufacturers))
                 .route("/health", get(openfda_health_check))
                 .route("/refresh-status", get(openfda_check_refresh_status))
+                // Device (UDI/GUDID) catalog
+                .route("/devices/search", get(atlas_pharma::handlers::openfda::search_devices))
+                .route("/devices/di/:primary_di", get(atlas_pharma::handlers::openfda::get_device_by_di))
+                .route("/devices/sync", post(atlas_pharma::handlers::openfda::trigger_device_sync))
+                // Drug enforcement reports (recalls)
+                .route("/recalls", get(atlas_pharma::handlers::openfda::search_recalls))
+                .route("/recalls/ndc/:ndc", get(atlas_pharma::handlers::openfda::get_recalls_by_ndc))
+                .route("/recalls/sync", post(atlas_pharma::handlers::openfda::trigger_recall_sync))
+                .route("/recalls/:recall_number", get(atlas_pharma::handlers::openfda::get_recall))
                 // Sync management (auth required)
                 .route("/sync", post(trigger_sync))
                 .route("/sync/active", get(get_active_sync))
                 .route("/sync/logs", get(get_openfda_sync_logs))
                 .route("/sync/:sync_id", get(get_sync_progress))
                 .route("/sync/:sync_id/cancel", post(cancel_sync))
+                .route("/sync/:sync_id/resume", post(atlas_pharma::handlers::openfda::resume_sync))
                 .route("/cleanup", post(openfda_cleanup_sync_logs))
                 .layer(middleware::from_fn_with_state(config.clone(), auth_middleware))
         )
@@ -286,9 +471,16 @@ pub fn create_app(config: AppConfig) -> Router {
             Router::new()
                 .route("/search", get(ema_search_catalog))
                 .route("/eu/:eu_number", get(get_by_eu_number))
+                .route("/eu/:eu_number/documents", get(atlas_pharma::handlers::ema::get_documents))
+                .route("/documents/search", get(atlas_pharma::handlers::ema::search_documents))
                 .route("/stats", get(ema_get_stats))
+                .route("/export", get(atlas_pharma::handlers::ema::export_catalog))
                 .route("/sync", post(ema_trigger_sync))
+                .route("/sync/background", post(atlas_pharma::handlers::ema::trigger_background_sync))
+                .route("/sync/active", get(atlas_pharma::handlers::ema::get_active_sync))
                 .route("/sync/logs", get(get_sync_logs))
+                .route("/sync/:sync_id", get(atlas_pharma::handlers::ema::get_sync_progress))
+                .route("/sync/:sync_id/cancel", post(atlas_pharma::handlers::ema::cancel_sync))
                 .route("/refresh-status", get(check_refresh_status))
                 .route("/config", get(ema_get_config_info))
                 .route("/cleanup", post(cleanup_sync_logs))
@@ -302,8 +494,15 @@ pub fn create_app(config: AppConfig) -> Router {
                 .route("/sessions", get(list_sessions))
                 .route("/session/:id", get(get_session))
                 .route("/session/:id/start-import", post(start_import))
+                .route("/session/:id/preview", get(preview_rows))
+                .route("/session/:id/row-decisions", put(update_row_decisions))
+                .route("/session/:id/rollback", post(rollback_import))
                 .route("/session/:id/rows", get(get_session_rows))
+                .route("/session/:id/extraction", get(get_extraction).put(review_extraction))
+                .route("/session/:id/extraction/apply", post(apply_extraction))
                 .route("/quota", get(get_user_quota))
+                .route("/mappings", get(list_remembered_mappings))
+                .route("/mappings/:id", delete(delete_remembered_mapping))
                 .layer(middleware::from_fn_with_state(config.clone(), auth_middleware))
         )
         .nest(
@@ -315,6 +514,11 @@ pub fn create_app(config: AppConfig) -> Router {
                 .route("/favorites", post(nl_query::save_favorite))
                 .route("/favorites", get(nl_query::get_favorites))
                 .route("/quota", get(nl_query::get_quota))
+                .route("/session/:id/export", get(nl_query::export_session))
+                .route("/reports/schedules", post(nl_query::create_report_schedule).get(nl_query::list_report_schedules))
+                .route("/reports/schedules/:id", put(nl_query::update_report_schedule).delete(nl_query::delete_report_schedule))
+                .route("/reports/files", get(nl_query::list_report_files))
+                .route("/reports/files/:id/download", get(nl_query::download_report_file))
                 .layer(middleware::from_fn_with_state(config.clone(), auth_middleware))
         )
         .nest(
@@ -323,8 +527,41 @@ pub fn create_app(config: AppConfig) -> Router {
                 .route("/inquiries/:inquiry_id/suggestions", post(inquiry_assistant::generate_suggestion))
                 .route("/suggestions/:suggestion_id", get(inquiry_assistant::get_suggestion))
                 .route("/suggestions/:suggestion_id/accept", post(inquiry_assistant::accept_suggestion))
+                .route("/suggestions/:suggestion_id/reject", post(inquiry_assistant::reject_suggestion))
+                .route("/inquiries/:inquiry_id/thread", get(inquiry_assistant::get_assistant_thread))
+                .route("/inquiries/:inquiry_id/thread", delete(inquiry_assistant::clear_assistant_thread))
                 .route("/inquiries/:inquiry_id/suggestions", get(inquiry_assistant::get_inquiry_suggestions))
                 .route("/quota", get(inquiry_assistant::get_quota))
+                .route("/preferences", get(inquiry_assistant::get_language_preferences))
+                .route("/preferences", put(inquiry_assistant::update_language_preferences))
+                .layer(middleware::from_fn_with_state(config.clone(), auth_middleware))
+        )
+        .nest(
+            "/api/ai-pricing",
+            Router::new()
+                .route("/suggestions", post(atlas_pharma::handlers::pricing_suggestions::generate_suggestion))
+                .route("/suggestions", get(atlas_pharma::handlers::pricing_suggestions::list_suggestions))
+                .route("/suggestions/:id", get(atlas_pharma::handlers::pricing_suggestions::get_suggestion))
+                .route("/quota", get(atlas_pharma::handlers::pricing_suggestions::get_quota))
+                .layer(middleware::from_fn_with_state(config.clone(), auth_middleware))
+        )
+        .nest(
+            "/api/ai-quota",
+            Router::new()
+                .route("/", get(atlas_pharma::handlers::ai_quota::get_usage))
+                .route("/tiers", get(atlas_pharma::handlers::ai_quota::list_tiers))
+                .route("/topups", post(atlas_pharma::handlers::ai_quota::purchase_topup))
+                .route("/topups", get(atlas_pharma::handlers::ai_quota::list_topups))
+                .layer(middleware::from_fn_with_state(config.clone(), auth_middleware))
+        )
+        .nest(
+            "/api/billing",
+            Router::new()
+                .route("/usage", get(atlas_pharma::handlers::billing::get_usage))
+                .route("/account", get(atlas_pharma::handlers::billing::get_account))
+                .route("/plans", get(atlas_pharma::handlers::billing::list_plans))
+                .route("/statements", get(atlas_pharma::handlers::billing::list_statements))
+                .route("/statements/:id", get(atlas_pharma::handlers::billing::get_statement))
                 .layer(middleware::from_fn_with_state(config.clone(), auth_middleware))
         )
         .nest(
@@ -334,15 +571,87 @@ pub fn create_app(config: AppConfig) -> Router {
                 .route("/notifications/unread-count", get(alerts::get_unread_count))
                 .route("/notifications/:id/read", put(alerts::mark_notification_read))
                 .route("/notifications/mark-all-read", post(alerts::mark_all_read))
+                .route("/notifications/archived", get(alerts::get_archived_notifications))
+                .route("/notifications/bulk-dismiss", post(alerts::bulk_dismiss_notifications))
                 .route("/notifications/:id", delete(alerts::dismiss_notification))
+                .route("/announcements", get(atlas_pharma::handlers::announcements::get_announcement_feed))
+                .route("/announcements/:id/read", post(atlas_pharma::handlers::announcements::mark_announcement_read))
+                .route("/stream", get(alerts::stream_notifications))
                 .route("/preferences", get(alerts::get_preferences))
                 .route("/preferences", put(alerts::update_preferences))
+                .route("/channels", get(alerts::get_channels))
+                .route("/channels", post(alerts::create_channel))
+                .route("/channels/:id", get(alerts::get_channel))
+                .route("/channels/:id", put(alerts::update_channel))
+                .route("/channels/:id", delete(alerts::delete_channel))
+                .route("/channels/:id/authorize", post(alerts::start_channel_authorization))
+                .route("/channels/:id/callback", post(alerts::complete_channel_authorization))
+                .route("/channels/:id/test", post(alerts::test_channel))
                 .route("/watchlist", get(alerts::get_watchlists))
                 .route("/watchlist", post(alerts::create_watchlist))
                 .route("/watchlist/:id", get(alerts::get_watchlist))
                 .route("/watchlist/:id", put(alerts::update_watchlist))
                 .route("/watchlist/:id", delete(alerts::delete_watchlist))
                 .route("/watchlist/:id/matches", get(alerts::get_watchlist_matches))
+                .route("/watchlist/:id/pause", post(alerts::pause_watchlist))
+                .route("/watchlist/:id/resume", post(alerts::resume_watchlist))
+                .route("/watchlist/:id/triggers", get(alerts::get_watchlist_triggers))
+                .route("/watchlist/:id/triggers", post(alerts::create_watchlist_trigger))
+                .route("/watchlist/:id/triggers/:trigger_id", put(alerts::update_watchlist_trigger))
+                .route("/watchlist/:id/triggers/:trigger_id", delete(alerts::delete_watchlist_trigger))
+                .layer(axum::Extension(realtime_hub.clone()))
+                .layer(middleware::from_fn_with_state(config.clone(), auth_middleware))
+        )
+        .nest(
+            "/api/catalog-subscriptions",
+            Router::new()
+                .route("/", get(atlas_pharma::handlers::catalog_subscriptions::list_subscriptions))
+                .route("/", post(atlas_pharma::handlers::catalog_subscriptions::create_subscription))
+                .route("/:id", get(atlas_pharma::handlers::catalog_subscriptions::get_subscription))
+                .route("/:id", put(atlas_pharma::handlers::catalog_subscriptions::update_subscription))
+                .route("/:id", delete(atlas_pharma::handlers::catalog_subscriptions::delete_subscription))
+                .route("/:id/events", get(atlas_pharma::handlers::catalog_subscriptions::get_subscription_events))
+                .layer(middleware::from_fn_with_state(config.clone(), auth_middleware))
+        )
+        .nest(
+            "/api/webhooks",
+            Router::new()
+                .route("/event-types", get(atlas_pharma::handlers::webhooks::list_event_types))
+                .route("/endpoints", get(atlas_pharma::handlers::webhooks::list_endpoints))
+                .route("/endpoints", post(atlas_pharma::handlers::webhooks::create_endpoint))
+                .route("/endpoints/:id", get(atlas_pharma::handlers::webhooks::get_endpoint))
+                .route("/endpoints/:id", put(atlas_pharma::handlers::webhooks::update_endpoint))
+                .route("/endpoints/:id", delete(atlas_pharma::handlers::webhooks::delete_endpoint))
+                .route("/endpoints/:id/rotate-secret", post(atlas_pharma::handlers::webhooks::rotate_endpoint_secret))
+                .route("/deliveries", get(atlas_pharma::handlers::webhooks::list_deliveries))
+                .route("/deliveries/:id", get(atlas_pharma::handlers::webhooks::get_delivery))
+                .route("/deliveries/:id/replay", post(atlas_pharma::handlers::webhooks::replay_delivery))
+                .layer(middleware::from_fn_with_state(config.clone(), auth_middleware))
+        )
+        .nest(
+            "/api/catalogs",
+            Router::new()
+                .route("/", get(atlas_pharma::handlers::regulator_catalogs::list_sources))
+                .route("/search", get(atlas_pharma::handlers::regulator_catalogs::federated_search))
+                .route("/:source/search", get(atlas_pharma::handlers::regulator_catalogs::search_catalog))
+                .route("/:source/products/:source_id", get(atlas_pharma::handlers::regulator_catalogs::get_product))
+                .route("/:source/stats", get(atlas_pharma::handlers::regulator_catalogs::get_stats))
+                .route("/:source/sync", post(atlas_pharma::handlers::regulator_catalogs::trigger_sync))
+                .route("/:source/sync/logs", get(atlas_pharma::handlers::regulator_catalogs::get_sync_logs))
+                .layer(middleware::from_fn_with_state(config.clone(), auth_middleware))
+        )
+        .nest(
+            "/api/search",
+            Router::new()
+                .route("/semantic", get(atlas_pharma::handlers::semantic_search::semantic_search))
+                .layer(middleware::from_fn_with_state(config.clone(), auth_middleware))
+        )
+        .nest(
+            "/api/verification",
+            Router::new()
+                .route("/lookup", post(atlas_pharma::handlers::product_verification::verification_lookup))
+                .route("/serials", post(atlas_pharma::handlers::product_verification::register_serial_numbers))
+                .route("/requests", get(atlas_pharma::handlers::product_verification::list_verification_requests))
                 .layer(middleware::from_fn_with_state(config.clone(), auth_middleware))
         )
         .nest(
@@ -350,11 +659,34 @@ pub fn create_app(config: AppConfig) -> Router {
             Router::new()
                 .route("/documents/generate", post(atlas_pharma::handlers::regulatory_documents::generate_document))
                 .route("/documents", get(atlas_pharma::handlers::regulatory_documents::list_documents))
+                .route("/documents/batches", get(atlas_pharma::handlers::document_batches::list_batches).post(atlas_pharma::handlers::document_batches::create_batch))
+                .route("/documents/batches/:id", get(atlas_pharma::handlers::document_batches::get_batch))
+                .route("/documents/batches/:id/download", get(atlas_pharma::handlers::document_batches::download_batch))
                 .route("/documents/:id", get(atlas_pharma::handlers::regulatory_documents::get_document))
-                .route("/documents/:id/approve", post(atlas_pharma::handlers::regulatory_documents::approve_document))
+                .route("/documents/:id/signers", put(atlas_pharma::handlers::regulatory_documents::set_document_signers))
+                .route("/documents/:id/signatures", get(atlas_pharma::handlers::regulatory_documents::get_document_signatures))
+                .route("/documents/:id/revise", post(atlas_pharma::handlers::regulatory_documents::revise_document))
+                .route("/documents/:id/obsolete", post(atlas_pharma::handlers::regulatory_documents::obsolete_document))
+                .route("/documents/:id/versions", get(atlas_pharma::handlers::regulatory_documents::get_document_versions))
+                .route("/documents/:id/diff", get(atlas_pharma::handlers::regulatory_documents::get_document_diff))
                 .route("/documents/:id/verify", get(atlas_pharma::handlers::regulatory_documents::verify_document))
                 .route("/documents/:id/audit-trail", get(atlas_pharma::handlers::regulatory_documents::get_audit_trail))
+                .route("/transactions/:id/dscsa", get(atlas_pharma::handlers::dscsa::get_transaction_dscsa_documents).post(atlas_pharma::handlers::dscsa::generate_transaction_dscsa_documents))
                 .route("/knowledge-base/stats", get(atlas_pharma::handlers::regulatory_documents::get_knowledge_base_stats))
+                .route("/knowledge-base", get(atlas_pharma::handlers::regulatory_knowledge_base::list_knowledge_entries).post(atlas_pharma::handlers::regulatory_knowledge_base::create_knowledge_entry))
+                .route("/knowledge-base/import", post(atlas_pharma::handlers::regulatory_knowledge_base::import_knowledge_document))
+                .route("/knowledge-base/sources", get(atlas_pharma::handlers::regulatory_knowledge_base::list_knowledge_sources))
+                .route("/knowledge-base/sources/versions", get(atlas_pharma::handlers::regulatory_knowledge_base::list_knowledge_source_versions))
+                .route("/knowledge-base/:id", get(atlas_pharma::handlers::regulatory_knowledge_base::get_knowledge_entry).put(atlas_pharma::handlers::regulatory_knowledge_base::update_knowledge_entry).delete(atlas_pharma::handlers::regulatory_knowledge_base::delete_knowledge_entry))
+                .route("/knowledge-base/:id/history", get(atlas_pharma::handlers::regulatory_knowledge_base::get_knowledge_entry_history))
+                // Signing re-authenticates with the password, so it shares the login budget
+                .merge(
+                    Router::new()
+                        .route("/documents/:id/approve", post(atlas_pharma::handlers::regulatory_documents::approve_document))
+                        .route("/documents/:id/sign", post(atlas_pharma::handlers::regulatory_documents::sign_document))
+                        .layer(middleware::from_fn(atlas_pharma::middleware::ip_rate_limiter::rate_limit_middleware))  // 🔒 RATE LIMITING
+                        .layer(axum::Extension(auth_rate_limiter.clone()))
+                )
                 .layer(middleware::from_fn_with_state(config.clone(), auth_middleware))
         )
         .nest(
@@ -366,12 +698,51 @@ pub fn create_app(config: AppConfig) -> Router {
                 .route("/connections/:id", get(atlas_pharma::handlers::erp_integration::get_connection))
                 .route("/connections/:id", delete(atlas_pharma::handlers::erp_integration::delete_connection))
                 .route("/connections/:id/test", post(atlas_pharma::handlers::erp_integration::test_connection))
+                .route("/connections/:id/entities", get(atlas_pharma::handlers::erp_integration::get_connection_entities))
+                .route("/connections/:id/entities", put(atlas_pharma::handlers::erp_integration::replace_connection_entities))
                 // Sync operations
                 .route("/connections/:id/sync", post(atlas_pharma::handlers::erp_integration::trigger_sync))
                 .route("/connections/:id/sync-logs", get(atlas_pharma::handlers::erp_integration::get_sync_logs))
                 // Mapping management
                 .route("/connections/:id/mappings", get(atlas_pharma::handlers::erp_integration::get_mappings))
+                .route("/connections/:id/mappings", post(atlas_pharma::handlers::erp_integration::create_mapping))
+                .route("/mappings/:id", put(atlas_pharma::handlers::erp_integration::update_mapping))
                 .route("/mappings/:id", delete(atlas_pharma::handlers::erp_integration::delete_mapping))
+                .route("/connections/:id/pending-mappings", get(atlas_pharma::handlers::erp_integration::get_pending_mappings))
+                .route("/connections/:id/pending-mappings/:pending_id/resolve", post(atlas_pharma::handlers::erp_integration::resolve_pending_mapping))
+                // Sandbox mode
+                .route("/connections/:id/sandbox", put(atlas_pharma::handlers::erp_integration::set_sandbox_mode))
+                .route("/connections/:id/sandbox-writes", get(atlas_pharma::handlers::erp_integration::get_sandbox_writes))
+                .route("/connections/:id/items", get(atlas_pharma::handlers::erp_integration::browse_erp_items))
+                // Conflict review
+                .route("/connections/:id/conflicts", get(atlas_pharma::handlers::erp_integration::list_conflicts))
+                .route("/conflicts/:id/resolve", post(atlas_pharma::handlers::erp_integration::resolve_conflict))
+                .route("/conflicts/:id/undo", post(atlas_pharma::handlers::erp_integration::undo_conflict_resolution))
+                .route("/connections/:id/order-export", put(atlas_pharma::handlers::erp_integration::update_order_export_settings))
+                .route("/transactions/:id/orders", get(atlas_pharma::handlers::erp_integration::get_transaction_orders))
+                .route("/transactions/:id/orders/retry", post(atlas_pharma::handlers::erp_integration::retry_transaction_orders))
+                // Accounting connectors (QuickBooks Online / Xero)
+                .route("/accounting-connections", get(atlas_pharma::handlers::accounting::list_accounting_connections))
+                .route("/accounting-connections", post(atlas_pharma::handlers::accounting::create_accounting_connection))
+                .route("/accounting-connections/:id", get(atlas_pharma::handlers::accounting::get_accounting_connection))
+                .route("/accounting-connections/:id", delete(atlas_pharma::handlers::accounting::delete_accounting_connection))
+                .route("/accounting-connections/:id/authorize", post(atlas_pharma::handlers::accounting::start_accounting_authorization))
+                .route("/accounting-connections/:id/callback", post(atlas_pharma::handlers::accounting::complete_accounting_authorization))
+                .route("/accounting-connections/:id/settings", put(atlas_pharma::handlers::accounting::update_accounting_settings))
+                .route("/transactions/:id/invoices", get(atlas_pharma::handlers::accounting::get_transaction_invoices))
+                .route("/transactions/:id/invoices/retry", post(atlas_pharma::handlers::accounting::retry_transaction_invoices))
+                // Field mapping templates (NetSuite/SAP)
+                .route("/field-mapping-templates", get(atlas_pharma::handlers::erp_integration::list_field_mapping_templates))
+                .route("/field-mapping-templates", post(atlas_pharma::handlers::erp_integration::create_field_mapping_template))
+                .route("/field-mapping-templates/:id", put(atlas_pharma::handlers::erp_integration::update_field_mapping_template))
+                .route("/field-mapping-templates/:id", delete(atlas_pharma::handlers::erp_integration::delete_field_mapping_template))
+                .route("/connections/:id/field-mapping", get(atlas_pharma::handlers::erp_integration::get_connection_field_mapping))
+                .route("/connections/:id/field-mapping", put(atlas_pharma::handlers::erp_integration::assign_connection_field_mapping))
+                // Flat-file (CSV over SFTP) connections
+                .route("/connections/:id/column-mapping", get(atlas_pharma::handlers::erp_integration::get_column_mapping))
+                .route("/connections/:id/column-mapping", put(atlas_pharma::handlers::erp_integration::update_column_mapping))
+                .route("/connections/:id/column-mapping/preview", post(atlas_pharma::handlers::erp_integration::preview_column_mapping))
+                .route("/connections/:id/file-transfers", get(atlas_pharma::handlers::erp_integration::get_file_transfers))
                 // AI-powered features
                 .route("/connections/:id/auto-discover-mappings", post(atlas_pharma::handlers::erp_ai_integration::auto_discover_mappings))
                 .route("/connections/:id/mapping-suggestions", get(atlas_pharma::handlers::erp_ai_integration::get_mapping_suggestions))
@@ -379,25 +750,104 @@ pub fn create_app(config: AppConfig) -> Router {
                 .route("/connections/:id/mapping-status", get(atlas_pharma::handlers::erp_ai_integration::get_mapping_status))
                 .route("/sync-logs/:id/ai-analysis", get(atlas_pharma::handlers::erp_ai_integration::get_sync_analysis))
                 .route("/connections/:id/resolve-conflicts", post(atlas_pharma::handlers::erp_ai_integration::suggest_conflict_resolution))
-                // Webhooks (public endpoints - no auth middleware)
+                .layer(middleware::from_fn_with_state(config.clone(), auth_middleware))
+                // Webhooks (public endpoints - authenticated by HMAC signature)
                 .route("/webhooks/netsuite/:id", post(atlas_pharma::handlers::erp_integration::netsuite_webhook))
                 .route("/webhooks/sap/:id", post(atlas_pharma::handlers::erp_integration::sap_webhook))
                 .with_state(config.database_pool.clone())
+        )
+        .nest(
+            "/api/edi",
+            Router::new()
+                // Trading partner profile
+                .route("/partner", get(atlas_pharma::handlers::edi::get_partner))
+                .route("/partner", put(atlas_pharma::handlers::edi::upsert_partner))
+                .route("/partner", delete(atlas_pharma::handlers::edi::delete_partner))
+                // Documents
+                .route("/documents", get(atlas_pharma::handlers::edi::list_documents))
+                .route("/documents/:id/raw", get(atlas_pharma::handlers::edi::get_document_raw))
+                .route("/transactions/:id/purchase-order", post(atlas_pharma::handlers::edi::send_purchase_order))
+                .route("/inbound", post(atlas_pharma::handlers::edi::upload_interchange))
+                .layer(middleware::from_fn_with_state(config.clone(), auth_middleware))
+                // AS2 receipt (public - partners authenticate with their inbound token)
+                .route("/as2/:partner_id", post(atlas_pharma::handlers::edi::as2_inbound))
+                .with_state(config.database_pool.clone())
+        )
+        .nest(
+            "/api/exports",
+            Router::new()
+                .route("/", get(atlas_pharma::handlers::data_exports::list_exports).post(atlas_pharma::handlers::data_exports::create_export))
+                .route("/:id", get(atlas_pharma::handlers::data_exports::get_export))
+                .layer(middleware::from_fn_with_state(config.clone(), auth_middleware))
+                // Download (public - authenticated by an expiring signed link)
+                .route("/:id/download", get(atlas_pharma::handlers::data_exports::download_export))
+        )
+        .nest(
+            "/fhir",
+            Router::new()
+                .route("/Medication", get(atlas_pharma::handlers::fhir::search_medication))
+                .route("/Medication/:id", get(atlas_pharma::handlers::fhir::read_medication))
+                .route("/MedicationKnowledge", get(atlas_pharma::handlers::fhir::search_medication_knowledge))
+                .route("/MedicationKnowledge/:id", get(atlas_pharma::handlers::fhir::read_medication_knowledge))
+                .route("/InventoryItem", get(atlas_pharma::handlers::fhir::search_inventory_item))
+                .route("/InventoryItem/:id", get(atlas_pharma::handlers::fhir::read_inventory_item))
+                .layer(middleware::from_fn_with_state(config.clone(), auth_middleware))
+                // Discovery (public - clients read it before they have a token)
+                .route("/metadata", get(atlas_pharma::handlers::fhir::capability_statement))
+                .route("/.well-known/smart-configuration", get(atlas_pharma::handlers::fhir::smart_configuration))
+                // Token exchange (public - authenticated by client credentials)
+                .merge(
+                    Router::new()
+                        .route("/auth/token", post(atlas_pharma::handlers::fhir::token))
+                        .layer(middleware::from_fn(atlas_pharma::middleware::ip_rate_limiter::rate_limit_middleware))  // 🔒 RATE LIMITING
+                        .layer(axum::Extension(auth_rate_limiter.clone()))
+                )
+        )
+        .nest(
+            "/api/fhir",
+            Router::new()
+                .route("/clients", get(atlas_pharma::handlers::fhir::list_fhir_clients).post(atlas_pharma::handlers::fhir::create_fhir_client))
+                .route("/clients/:id", delete(atlas_pharma::handlers::fhir::revoke_fhir_client))
+                .layer(middleware::from_fn_with_state(config.clone(), auth_middleware))
+        )
+        .nest(
+            "/api/batch",
+            Router::new()
+                .route("/", post(atlas_pharma::handlers::batch_operations::create_batch_operation))
+                .route("/:id", get(atlas_pharma::handlers::batch_operations::get_batch_operation))
+                .route("/:id/results", get(atlas_pharma::handlers::batch_operations::get_batch_operation_results))
                 .layer(middleware::from_fn_with_state(config.clone(), auth_middleware))
         )
+        .nest(
+            "/graphql",
+            Router::new()
+                .route("/", post(atlas_pharma::handlers::graphql::graphql))
+                .layer(middleware::from_fn_with_state(config.clone(), auth_middleware))
+        )
+        // 📖 API DOCS: OpenAPI 3.1 spec and Swagger UI (public)
+        .route("/api/openapi.json", get(atlas_pharma::openapi::openapi_json))
+        .merge(utoipa_swagger_ui::SwaggerUi::new("/api/docs").config(utoipa_swagger_ui::Config::from("/api/openapi.json")))
         // 📊 OBSERVABILITY: Prometheus metrics endpoint (public)
         .route("/metrics", get(atlas_pharma::middleware::metrics_handler))
         .layer(
             ServiceBuilder::new()
                 .layer(middleware::from_fn(atlas_pharma::middleware::metrics_middleware))  // 📊 OBSERVABILITY: Prometheus metrics collection
-                .layer(middleware::from_fn(atlas_pharma::middleware::content_type_validation_middleware))  // 🔒 SECURITY: Content-Type validation
                 .layer(middleware::from_fn(atlas_pharma::middleware::request_id_middleware))  // 📊 OBSERVABILITY: Request ID tracking for distributed tracing
+                .layer(middleware::from_fn(atlas_pharma::middleware::problem_details_middleware))  // 🧾 Every error response as RFC 7807 problem+json, localized, with the request id
+                .layer(middleware::from_fn(atlas_pharma::middleware::field_selection_middleware))  // ✂️  ?fields= sparse fieldsets of JSON responses
+                .layer(middleware::from_fn(atlas_pharma::middleware::content_type_validation_middleware))  // 🔒 SECURITY: Content-Type validation
+                .layer(middleware::from_fn(atlas_pharma::middleware::request_logging_middleware))  // 📊 OBSERVABILITY: Structured, sampled request logs
+                .layer(middleware::from_fn(atlas_pharma::middleware::error_tracking_middleware))  // 📊 OBSERVABILITY: Per-request error tracking scope (request id, route, user)
+                .layer(CatchPanicLayer::custom(atlas_pharma::middleware::panic_response))  // 📊 OBSERVABILITY: Panics become 500s (already reported by the panic hook)
                 .layer(middleware::from_fn(atlas_pharma::middleware::security_headers_middleware))  // 🔒 SECURITY: Production security headers (OWASP, PCI DSS, SOC 2)
                 .layer(axum::Extension(audit_service.clone()))  // 📋 Audit logging for compliance
                 .layer(axum::Extension(token_blacklist.clone()))  // 🔒 Token blacklist for logout/revocation
                 .layer(axum::Extension(api_rate_limiter))  // 🔒 Rate limiter for DDoS protection
                 .layer(middleware::from_fn(atlas_pharma::middleware::ip_rate_limiter::rate_limit_middleware))  // 🔒 Rate limiting middleware
                 .layer(cors)
+                .layer(middleware::from_fn_with_state(config.clone(), atlas_pharma::middleware::maintenance_middleware))  // 🚧 Maintenance mode: 503 + Retry-After for non-admin traffic
+                .layer(middleware::from_fn_with_state(config.clone(), atlas_pharma::middleware::payload_capture_middleware))  // 🔒 COMPLIANCE: Redacted bodies of requests matching an open capture rule
+                .layer(middleware::from_fn_with_state(config.clone(), atlas_pharma::middleware::replica_routing_middleware))  // 🗄️  Read replica: users who just wrote read their own writes from the primary
                 .layer(axum::middleware::from_fn_with_state(
                     config.clone(),
                     |state: State<atlas_pharma::config::AppConfig>, req: Request<_>, next: Next| async move {
@@ -409,7 +859,8 @@ pub fn create_app(config: AppConfig) -> Router {
                         if let Some(auth_header) = auth_header {
                             if let Some(token) = atlas_pharma::middleware::JwtService::extract_token_from_header(auth_header) {
                                 let jwt_service = atlas_pharma::middleware::JwtService::new(&state.jwt_secret);
-                                if let Ok(claims) = jwt_service.validate_token(token) {
+                                if let Some(claims) = jwt_service.validate_token(token).ok().filter(|claims| claims.allows_path(req.uri().path())) {
+                                    atlas_pharma::middleware::record_request_user(claims.user_id);
                                     let mut req = req;
                                     req.extensions_mut().insert(claims);
                                     return Ok::<axum::response::Response, atlas_pharma::middleware::error_handling::AppError>(next.run(req).await);
@@ -436,12 +887,57 @@ pub fn create_app(config: AppConfig) -> Router {
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    // 📊 Error tracking first, so startup panics are reported too; the guard
+    // flushes queued events on shutdown
+    let error_tracking = atlas_pharma::middleware::error_tracking::init();
+
+    // 🚦 Preflight: refuse to boot on invalid configuration or a database with pending
+    // migrations, listing every problem at once
+    let mut preflight = atlas_pharma::config::preflight::check_environment()?;
     let config = atlas_pharma::config::AppConfig::from_env().await?;
     let tls_config = atlas_pharma::config::tls::TlsConfig::from_env()?;
+    atlas_pharma::config::preflight::check_migrations(&config.database_pool, &mut preflight).await?;
+
+    // ⚙️ Runtime settings must be loaded before the app is built (rate limits, CORS)
+    let settings_loaded = atlas_pharma::services::SettingsService::new(config.database_pool.clone())
+        .reload()
+        .await;
 
     // Create app (this initializes the logger)
     let app = create_app(config.clone());
 
+    preflight.log();
+
+    match settings_loaded {
+        Ok(()) => tracing::info!("⚙️  Runtime settings loaded"),
+        Err(e) => tracing::warn!("⚠️  Runtime settings unavailable, using defaults: {:?}", e),
+    }
+
+    // 🔒 SECURITY: Check every route is authenticated (admin routes admin-only) or
+    // deliberately public; tests/route_policies.rs runs the same check in CI
+    let route_policies = atlas_pharma::middleware::RoutePolicyReport::from_source(include_str!("main.rs"));
+    for violation in &route_policies.violations {
+        tracing::error!("🚨 Route policy violation ({}): {} {} - {}", violation.rule, violation.method, violation.path, violation.message);
+    }
+    if route_policies.is_clean() {
+        tracing::info!("🔒 Route policies verified ({} routes)", route_policies.route_count);
+    } else if std::env::var("ROUTE_POLICY_STRICT").map(|v| v == "true").unwrap_or(false) {
+        anyhow::bail!("{} route policy violations (ROUTE_POLICY_STRICT=true)", route_policies.violations.len());
+    }
+    atlas_pharma::middleware::install_route_policy_report(route_policies);
+
+    if error_tracking.is_some() {
+        tracing::info!("📊 Error tracking enabled");
+    } else {
+        tracing::info!("📊 Error tracking disabled (no SENTRY_DSN or ERROR_TRACKING_ENABLED=false)");
+    }
+
+    // 🛑 Graceful shutdown: SIGTERM/Ctrl+C stop schedulers and drain in-flight work
+    tokio::spawn(Shutdown::listen());
+
+    // 👑 Leader election: schedulers that must run once across replicas only run on their leader
+    atlas_pharma::services::SchedulerLeader::init(config.database_pool.clone());
+
     // 🔒 SECURITY: Initialize API Quota Service
     tracing::info!("🔐 Initializing API Quota Service...");
     let quota_service = atlas_pharma::services::ApiQuotaService::new(config.database_pool.clone());
@@ -482,33 +978,108 @@ async fn main() -> anyhow::Result<()> {
         Err(e) => tracing::warn!("⚠️  Could not check key rotation status: {}", e),
     }
 
-    // Start background alert scheduler
+    // 🔑 Encrypt with the active data encryption key; keys rotated on any instance are
+    // picked up by the refresh before they activate
+    match key_rotation_service.load_keyring().await {
+        Ok(0) => tracing::info!("✅ Encryption keyring loaded (no data key active yet, using ENCRYPTION_KEY)"),
+        Ok(version) => tracing::info!("✅ Encryption keyring loaded (encrypting with key version {})", version),
+        Err(e) => tracing::error!("❌ Failed to load the encryption keyring: {}", e),
+    }
+    let keyring_pool = config.database_pool.clone();
+    let keyring_master_key = config.encryption_key.clone();
+    tokio::spawn(async move {
+        atlas_pharma::services::EncryptionKeyRotationService::new(keyring_pool, keyring_master_key)
+            .run_keyring_refresh()
+            .await;
+    });
+
+    // Start background alert scheduler (expiry, low stock, watchlists, digests). Each check
+    // type runs on its own interval under a lease, so only one replica runs it at a time.
     let scheduler_pool = config.database_pool.clone();
     tokio::spawn(async move {
         use atlas_pharma::services::AlertSchedulerService;
-        use std::time::Duration;
 
         let scheduler = AlertSchedulerService::new(scheduler_pool);
-        let mut interval = tokio::time::interval(Duration::from_secs(3600)); // Run every hour
+        tracing::info!("🔔 Alert scheduler started - check intervals are configured in alert_scheduler_jobs");
+        scheduler.run().await;
+    });
+
+    // Start background job worker (manual ERP syncs, AI imports, OpenFDA syncs, document
+    // batches, manual sanctions list syncs). Jobs are persisted and leased, so work that is
+    // interrupted by a restart is picked up again by any replica.
+    let job_worker_config = config.clone();
+    tokio::spawn(async move {
+        use atlas_pharma::services::BackgroundJobWorker;
 
-        tracing::info!("🔔 Alert scheduler started - checking alerts every hour");
+        let worker = BackgroundJobWorker::new(job_worker_config);
+        tracing::info!("🧵 Background job worker started - limits are configured in background_job_types");
+        worker.run().await;
+    });
+
+    // Start runtime settings listener (reloads settings changed on any instance)
+    tokio::spawn(atlas_pharma::services::SettingsService::new(config.database_pool.clone()).run_listener());
+    tracing::info!("⚙️  Runtime settings listener started");
+
+    // Start real-time notification listener (LISTEN/NOTIFY fan-out to SSE streams)
+    tokio::spawn(realtime_hub.run_listener(config.database_pool.clone()));
+    tracing::info!("📡 Real-time notification listener started");
+
+    // Start outbound webhook delivery worker
+    let webhook_pool = config.database_pool.clone();
+    tokio::spawn(async move {
+        use atlas_pharma::services::OutboundWebhookService;
+        use std::time::Duration;
+
+        let service = OutboundWebhookService::new(webhook_pool);
+        let mut interval = tokio::time::interval(Duration::from_secs(15));
+
+        tracing::info!("📤 Webhook delivery worker started - sending due deliveries every 15 seconds");
 
         loop {
             interval.tick().await;
+            if atlas_pharma::services::MaintenanceService::pauses("webhook_delivery") {
+                continue;
+            }
+
+            if let Err(e) = service.process_due_deliveries(100).await {
+                tracing::error!("❌ Webhook delivery run failed: {}", e);
+            }
+        }
+    });
+
+    // Start marketplace anomaly detector
+    let anomaly_pool = config.database_pool.clone();
+    tokio::spawn(async move {
+        use atlas_pharma::services::AnomalyDetectionService;
+        use std::time::Duration;
+
+        let detector = AnomalyDetectionService::new(anomaly_pool);
+        let mut interval = tokio::time::interval(Duration::from_secs(6 * 3600)); // Run every 6 hours
 
-            tracing::info!("🔄 Running scheduled alert checks...");
+        tracing::info!("🔎 Anomaly detector started - scanning inventory and transactions every 6 hours");
 
-            match scheduler.run_scheduled_checks().await {
+        loop {
+            interval.tick().await;
+            if atlas_pharma::services::MaintenanceService::pauses("anomaly_detection")
+                || !atlas_pharma::services::SchedulerLeader::leads("anomaly_detection").await
+            {
+                continue;
+            }
+
+            match detector.run_detection().await {
                 Ok(stats) => {
                     tracing::info!(
-                        "✅ Alert check completed: {} expiry, {} low stock, {} watchlist alerts generated",
-                        stats.expiry_alerts_generated,
-                        stats.low_stock_alerts_generated,
-                        stats.watchlist_alerts_generated
+                        "✅ Anomaly scan completed: {} new findings ({} quantity, {} listing price, {} transaction price, {} duplicate), {} errors",
+                        stats.total_new,
+                        stats.quantity_jumps,
+                        stats.listing_price_outliers,
+                        stats.transaction_price_outliers,
+                        stats.duplicate_listings,
+                        stats.errors_encountered
                     );
                 }
                 Err(e) => {
-                    tracing::error!("❌ Alert check failed: {}", e);
+                    tracing::error!("❌ Anomaly scan failed: {}", e);
                 }
             }
         }
@@ -524,6 +1095,174 @@ async fn main() -> anyhow::Result<()> {
         scheduler.run().await;
     });
 
+    // Start EMA sync scheduler (weekly sync)
+    let ema_scheduler_pool = config.database_pool.clone();
+    tokio::spawn(async move {
+        use atlas_pharma::services::ema_service::EmaSyncScheduler;
+
+        let scheduler = EmaSyncScheduler::new(ema_scheduler_pool);
+        tracing::info!("🇪🇺 EMA sync scheduler initialized");
+        scheduler.run().await;
+    });
+
+    // Start OpenFDA recall sync scheduler (daily incremental sync)
+    let recall_scheduler_pool = config.database_pool.clone();
+    tokio::spawn(async move {
+        use atlas_pharma::services::openfda_recall_service::OpenFdaRecallSyncScheduler;
+
+        let scheduler = OpenFdaRecallSyncScheduler::new(recall_scheduler_pool);
+        tracing::info!("🚨 OpenFDA recall sync scheduler initialized");
+        scheduler.run().await;
+    });
+
+    // Start regulator catalog sync scheduler (MHRA, Health Canada, Swissmedic; weekly)
+    let regulator_scheduler_pool = config.database_pool.clone();
+    tokio::spawn(async move {
+        use atlas_pharma::services::regulator_catalogs::RegulatorCatalogSyncScheduler;
+
+        let scheduler = RegulatorCatalogSyncScheduler::new(regulator_scheduler_pool);
+        tracing::info!("🌐 Regulator catalog sync scheduler initialized");
+        scheduler.run().await;
+    });
+
+    // Start ERP sync scheduler (every connection on its own sync frequency)
+    let erp_scheduler_pool = config.database_pool.clone();
+    tokio::spawn(async move {
+        use atlas_pharma::services::erp::ErpSyncScheduler;
+
+        let scheduler = ErpSyncScheduler::new(erp_scheduler_pool);
+        tracing::info!("🔄 ERP sync scheduler initialized");
+        scheduler.run().await;
+    });
+
+    // Start EDI poll scheduler (inbound 855/856/810 files from SFTP trading partners)
+    let edi_scheduler_pool = config.database_pool.clone();
+    tokio::spawn(async move {
+        use atlas_pharma::services::edi::EdiPollScheduler;
+
+        let scheduler = EdiPollScheduler::new(edi_scheduler_pool);
+        tracing::info!("📨 EDI poll scheduler initialized");
+        scheduler.run().await;
+    });
+
+    // Start semantic index scheduler (embeds new and changed catalog/inventory records)
+    let semantic_scheduler_pool = config.database_pool.clone();
+    tokio::spawn(async move {
+        use atlas_pharma::services::SemanticIndexScheduler;
+
+        let scheduler = SemanticIndexScheduler::new(semantic_scheduler_pool);
+        tracing::info!("🧭 Semantic index scheduler initialized");
+        scheduler.run().await;
+    });
+
+    // Start AI quota reset scheduler (monthly counters, top-ups and expired overrides)
+    let quota_reset_pool = config.database_pool.clone();
+    tokio::spawn(async move {
+        use atlas_pharma::services::AiQuotaResetScheduler;
+
+        let scheduler = AiQuotaResetScheduler::new(quota_reset_pool);
+        tracing::info!("🎚️  AI quota reset scheduler initialized");
+        scheduler.run().await;
+    });
+
+    // Start usage metering scheduler (API call flushes, gauge samples, monthly statements)
+    let metering_pool = config.database_pool.clone();
+    tokio::spawn(async move {
+        use atlas_pharma::services::UsageMeteringScheduler;
+
+        let scheduler = UsageMeteringScheduler::new(metering_pool);
+        tracing::info!("🧾 Usage metering scheduler initialized");
+        scheduler.run().await;
+    });
+
+    // Start payload capture scheduler (rule reloads across instances, expired capture purge)
+    let capture_scheduler_pool = config.database_pool.clone();
+    tokio::spawn(async move {
+        use atlas_pharma::services::PayloadCaptureScheduler;
+
+        let scheduler = PayloadCaptureScheduler::new(capture_scheduler_pool);
+        tracing::info!("🎙️  Payload capture scheduler initialized");
+        scheduler.run().await;
+    });
+
+    // Start read replica lag monitor (reads fall back to the primary while it lags)
+    if let Some(read_replica) = config.read_replica.clone() {
+        let replica_monitor_pool = config.database_pool.clone();
+        tokio::spawn(async move {
+            tracing::info!("🗄️  Read replica lag monitor initialized");
+            read_replica.run_lag_monitor(replica_monitor_pool).await;
+        });
+    }
+
+    // Start database pool monitor (saturation metrics, warns while a pool is nearly exhausted)
+    let pool_monitor_config = config.clone();
+    tokio::spawn(async move {
+        tracing::info!("🗄️  Database pool monitor initialized");
+        atlas_pharma::config::pool::run_pool_monitor(pool_monitor_config).await;
+    });
+
+    // Start search index worker (mirrors listings and catalogs into SEARCH_ENGINE;
+    // without one it disables the index triggers and exits)
+    let search_index_pool = config.database_pool.clone();
+    let search_engine = config.search_engine.clone();
+    tokio::spawn(async move {
+        use atlas_pharma::services::search_engine::SearchIndexWorker;
+
+        let worker = SearchIndexWorker::new(search_index_pool, search_engine);
+        tracing::info!("🔎 Search index worker initialized");
+        worker.run().await;
+    });
+
+    // Start data export purge scheduler (expires finished exports and deletes their files)
+    let export_purge_service = atlas_pharma::services::DataExportService::new(
+        config.database_pool.clone(),
+        &config.file_storage_path,
+        &config.encryption_key,
+    );
+    tokio::spawn(async move {
+        use atlas_pharma::services::DataExportPurgeScheduler;
+
+        let scheduler = DataExportPurgeScheduler::new(export_purge_service);
+        tracing::info!("📦 Data export purge scheduler initialized");
+        scheduler.run().await;
+    });
+
+    // Start summary refresh scheduler (stale materialized views, daily counter recount)
+    let summary_pool = config.database_pool.clone();
+    tokio::spawn(async move {
+        use atlas_pharma::services::SummaryRefreshScheduler;
+
+        let scheduler = SummaryRefreshScheduler::new(summary_pool);
+        tracing::info!("📈 Summary refresh scheduler initialized");
+        scheduler.run().await;
+    });
+
+    // Start query cache sync watcher (flushes catalog caches after syncs on any instance)
+    let cache_watcher_pool = config.database_pool.clone();
+    tokio::spawn(async move {
+        use atlas_pharma::services::QueryCacheSyncWatcher;
+
+        let watcher = QueryCacheSyncWatcher::new(cache_watcher_pool);
+        tracing::info!("🗄️  Query cache sync watcher initialized");
+        watcher.run().await;
+    });
+
+    // Start NL query report scheduler (daily/weekly saved-query reports)
+    let report_scheduler_pool = config.database_pool.clone();
+    let report_storage_path = config.file_storage_path.clone();
+    let report_encryption_key = config.encryption_key.clone();
+    tokio::spawn(async move {
+        use atlas_pharma::services::NlReportScheduler;
+
+        match NlReportScheduler::new(report_scheduler_pool, &report_storage_path, &report_encryption_key) {
+            Ok(scheduler) => {
+                tracing::info!("📊 NL query report scheduler initialized");
+                scheduler.run().await;
+            }
+            Err(e) => tracing::error!("❌ NL query report scheduler failed to start: {}", e),
+        }
+    });
+
     // Start server with TLS if enabled, otherwise use plain HTTP
     if tls_config.enabled {
         let rustls_config = tls_config.build_rustls_config().await?;
@@ -531,7 +1270,16 @@ async fn main() -> anyhow::Result<()> {
 
         tracing::info!("🔒 Starting Atlas Pharma server with TLS on https://{}", addr);
 
+        // 🛑 Stop accepting connections on shutdown; open ones get the rest of the drain timeout
+        let handle = axum_server::Handle::new();
+        let shutdown_handle = handle.clone();
+        tokio::spawn(async move {
+            Shutdown::wait().await;
+            shutdown_handle.graceful_shutdown(Some(Shutdown::remaining()));
+        });
+
         axum_server::bind_rustls(addr, rustls_config)
+            .handle(handle)
             .serve(app.into_make_service_with_connect_info::<std::net::SocketAddr>())
             .await?;
     } else {
@@ -542,11 +1290,22 @@ async fn main() -> anyhow::Result<()> {
         tracing::info!("💡 To enable TLS, set TLS_ENABLED=true in .env and configure certificates");
 
         let listener = tokio::net::TcpListener::bind(addr).await?;
-        axum::serve(
+        let server = axum::serve(
             listener,
             app.into_make_service_with_connect_info::<std::net::SocketAddr>()
-        ).await?;
+        ).with_graceful_shutdown(Shutdown::wait());
+
+        // axum::serve waits for every connection, so cut it off at the drain timeout
+        tokio::select! {
+            result = server => result?,
+            _ = Shutdown::deadline() => tracing::warn!("⏱️  Drain timeout reached with connections still open"),
+        }
     }
 
+    // 🛑 Hand back in-flight jobs and syncs before exiting
+    let handed_back = Shutdown::drain(&config.database_pool).await;
+    atlas_pharma::services::SchedulerLeader::release_all().await;
+    tracing::info!("👋 Atlas Pharma server stopped ({} in-flight run(s) handed back)", handed_back);
+
     Ok(())
 }
\ No newline at end of file
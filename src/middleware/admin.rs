@@ -188,6 +188,7 @@ mod tests {
             exp: 9999999999,
             iat: 1234567890,
             jti: Uuid::new_v4().to_string(),
+            audience: None,
         }
     }
 
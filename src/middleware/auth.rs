@@ -1,6 +1,6 @@
 use axum::{
-    extract::{Request, State},
-    http::{header, StatusCode},
+    extract::{OriginalUri, Request, State},
+    http::{header, HeaderMap, StatusCode},
     middleware::Next,
     response::Response,
 };
@@ -12,6 +12,9 @@ use uuid::Uuid;
 use crate::config::AppConfig;
 use crate::models::user::UserRole;
 
+/// Audience of the tokens issued to FHIR clients; they are only accepted under `/fhir`
+pub const FHIR_TOKEN_AUDIENCE: &str = "fhir";
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Claims {
     pub sub: String,
@@ -23,6 +26,9 @@ pub struct Claims {
     pub exp: usize,
     pub iat: usize,
     pub jti: String,  // JWT ID for token blacklist
+    /// API a restricted token is limited to (`FHIR_TOKEN_AUDIENCE`); None for sessions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audience: Option<String>,
 }
 
 impl Claims {
@@ -35,6 +41,16 @@ impl Claims {
     pub fn is_superadmin(&self) -> bool {
         self.role.is_superadmin()
     }
+
+    /// Whether the token may be used on `path`: restricted tokens only work under the
+    /// prefix of their audience
+    pub fn allows_path(&self, path: &str) -> bool {
+        match self.audience.as_deref() {
+            None => true,
+            Some(FHIR_TOKEN_AUDIENCE) => path == "/fhir" || path.starts_with("/fhir/"),
+            Some(_) => false,
+        }
+    }
 }
 
 pub struct JwtService {
@@ -51,19 +67,55 @@ impl JwtService {
     }
 
     pub fn generate_token(&self, user_id: Uuid, email: &str, company_name: &str, is_verified: bool, role: UserRole) -> Result<String, jsonwebtoken::errors::Error> {
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as usize;
-
         // Admin session timeout: 2 hours (more secure)
         // Regular user: 24 hours
-        let exp = if role.is_admin() {
-            now + 2 * 60 * 60  // 2 hours for admins
+        let ttl_secs = if role.is_admin() {
+            2 * 60 * 60  // 2 hours for admins
         } else {
-            now + 24 * 60 * 60  // 24 hours for regular users
+            24 * 60 * 60  // 24 hours for regular users
         };
 
+        self.sign(user_id, email, company_name, is_verified, role, ttl_secs, None)
+    }
+
+    /// Short-lived token of a FHIR client: read access under `/fhir` only, and never with
+    /// the admin rights the registering account may have
+    pub fn generate_fhir_token(
+        &self,
+        user_id: Uuid,
+        email: &str,
+        company_name: &str,
+        is_verified: bool,
+        ttl_secs: usize,
+    ) -> Result<String, jsonwebtoken::errors::Error> {
+        self.sign(
+            user_id,
+            email,
+            company_name,
+            is_verified,
+            UserRole::User,
+            ttl_secs,
+            Some(FHIR_TOKEN_AUDIENCE.to_string()),
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn sign(
+        &self,
+        user_id: Uuid,
+        email: &str,
+        company_name: &str,
+        is_verified: bool,
+        role: UserRole,
+        ttl_secs: usize,
+        audience: Option<String>,
+    ) -> Result<String, jsonwebtoken::errors::Error> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as usize;
+        let exp = now + ttl_secs;
+
         let claims = Claims {
             sub: user_id.to_string(),
             user_id,
@@ -74,6 +126,7 @@ impl JwtService {
             exp,
             iat: now,
             jti: Uuid::new_v4().to_string(),  // Unique token ID for blacklist tracking
+            audience,
         };
 
         encode(&Header::default(), &claims, &self.encoding_key)
@@ -97,6 +150,23 @@ impl JwtService {
     }
 }
 
+/// Claims of the request's token (cookie first, then bearer) without the blacklist
+/// checks; for outer layers that only need to know who is calling. Access control
+/// stays with `auth_middleware`.
+pub fn peek_request_claims(config: &AppConfig, headers: &HeaderMap) -> Option<Claims> {
+    let cookie_jar = CookieJar::from_headers(headers);
+    let token = match cookie_jar.get("auth_token") {
+        Some(cookie) => Some(cookie.value().to_string()),
+        None => headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(JwtService::extract_token_from_header)
+            .map(str::to_string),
+    };
+
+    token.and_then(|token| JwtService::new(&config.jwt_secret).validate_token(&token).ok())
+}
+
 pub async fn auth_middleware(
     State(config): State<AppConfig>,
     mut request: Request,
@@ -125,42 +195,77 @@ pub async fn auth_middleware(
             .and_then(JwtService::extract_token_from_header)
     };
 
-    if let Some(token) = token {
-        match jwt_service.validate_token(token) {
-            Ok(claims) => {
-                // 🔒 SECURITY: Check if token is blacklisted (logout/revoked)
-                if let Some(ref blacklist) = blacklist {
-                    if blacklist.is_blacklisted(&claims.jti) {
-                        tracing::warn!("Blocked blacklisted token for user {}", claims.user_id);
-                        return Err(StatusCode::UNAUTHORIZED);
-                    }
-                }
+    let Some(token) = token else {
+        return Err(StatusCode::UNAUTHORIZED);
+    };
+    // Nested routers see their path without the prefix
+    let path = request
+        .extensions()
+        .get::<OriginalUri>()
+        .map(|uri| uri.path())
+        .unwrap_or_else(|| request.uri().path());
+    let claims = authorize(&jwt_service, blacklist.as_deref(), token, path)?;
 
-                request.extensions_mut().insert(claims);
-                return Ok(next.run(request).await);
-            }
-            Err(_) => return Err(StatusCode::UNAUTHORIZED),
+    crate::middleware::record_request_user(claims.user_id);
+    crate::services::UsageMeteringService::count_api_call(claims.user_id);
+    request.extensions_mut().insert(claims);
+    Ok(next.run(request).await)
+}
+
+/// Claims of `token` if it may be used on `path`
+fn authorize(
+    jwt_service: &JwtService,
+    blacklist: Option<&crate::services::TokenBlacklistService>,
+    token: &str,
+    path: &str,
+) -> Result<Claims, StatusCode> {
+    let claims = jwt_service.validate_token(token).map_err(|_| StatusCode::UNAUTHORIZED)?;
+
+    // 🔒 SECURITY: Check if token is blacklisted (logout/revoked)
+    if let Some(blacklist) = blacklist {
+        if blacklist.is_blacklisted(&claims.jti) {
+            tracing::warn!("Blocked blacklisted token for user {}", claims.user_id);
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+        // 🔒 SECURITY: Sessions revoked for the whole account (suspension)
+        if blacklist.is_session_revoked(claims.user_id, claims.iat) {
+            tracing::warn!("Blocked revoked session for user {}", claims.user_id);
+            return Err(StatusCode::UNAUTHORIZED);
         }
     }
 
-    Err(StatusCode::UNAUTHORIZED)
+    // 🔒 SECURITY: FHIR client tokens only work on the FHIR API
+    if !claims.allows_path(path) {
+        tracing::warn!("Blocked {:?} token of user {} on {}", claims.audience, claims.user_id, path);
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    Ok(claims)
 }
 
+/// Attach the caller's claims when the request carries a valid token (cookie or
+/// bearer), and let anonymous requests through. Revoked tokens count as anonymous.
 pub fn create_optional_auth_middleware() -> impl Fn(State<AppConfig>, Request, Next) -> Pin<Box<dyn Future<Output = Result<Response, StatusCode>> + Send>> {
     move |State(config): State<AppConfig>, mut request: Request, next: Next| {
         Box::pin(async move {
-            let auth_header = request
-                .headers()
-                .get(header::AUTHORIZATION)
-                .and_then(|value| value.to_str().ok());
-
-            if let Some(auth_header) = auth_header {
-                if let Some(token) = JwtService::extract_token_from_header(auth_header) {
-                    let jwt_service = JwtService::new(&config.jwt_secret);
-                    
-                    if let Ok(claims) = jwt_service.validate_token(token) {
-                        request.extensions_mut().insert(claims);
-                    }
+            if let Some(claims) = peek_request_claims(&config, request.headers()) {
+                use crate::services::TokenBlacklistService;
+                use std::sync::Arc;
+                let revoked = request
+                    .extensions()
+                    .get::<Arc<TokenBlacklistService>>()
+                    .is_some_and(|blacklist| {
+                        blacklist.is_blacklisted(&claims.jti) || blacklist.is_session_revoked(claims.user_id, claims.iat)
+                    });
+
+                let path = request
+                    .extensions()
+                    .get::<OriginalUri>()
+                    .map(|uri| uri.path())
+                    .unwrap_or_else(|| request.uri().path());
+                if !revoked && claims.allows_path(path) {
+                    crate::middleware::record_request_user(claims.user_id);
+                    request.extensions_mut().insert(claims);
                 }
             }
 
@@ -170,4 +275,38 @@ pub fn create_optional_auth_middleware() -> impl Fn(State<AppConfig>, Request, N
 }
 
 use std::pin::Pin;
-use futures::Future;
\ No newline at end of file
+use futures::Future;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fhir_token_is_refused_outside_fhir() {
+        let jwt_service = JwtService::new("test-secret");
+        let token = jwt_service
+            .generate_fhir_token(Uuid::new_v4(), "pharmacy@hospital.example", "General Hospital", true, 300)
+            .unwrap();
+
+        let claims = authorize(&jwt_service, None, &token, "/fhir/Medication").unwrap();
+        assert_eq!(claims.audience.as_deref(), Some(FHIR_TOKEN_AUDIENCE));
+        assert_eq!(claims.role, UserRole::User);
+
+        assert_eq!(authorize(&jwt_service, None, &token, "/api/inventory/my").unwrap_err(), StatusCode::FORBIDDEN);
+        assert_eq!(authorize(&jwt_service, None, &token, "/api/admin/users").unwrap_err(), StatusCode::FORBIDDEN);
+        assert_eq!(authorize(&jwt_service, None, &token, "/fhirish").unwrap_err(), StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn test_session_token_works_everywhere() {
+        let jwt_service = JwtService::new("test-secret");
+        let token = jwt_service
+            .generate_token(Uuid::new_v4(), "admin@atlas.example", "Atlas", true, UserRole::Admin)
+            .unwrap();
+
+        let claims = authorize(&jwt_service, None, &token, "/api/admin/users").unwrap();
+        assert_eq!(claims.audience, None);
+        assert!(authorize(&jwt_service, None, &token, "/fhir/Medication").is_ok());
+        assert_eq!(authorize(&jwt_service, None, "not-a-token", "/api/inventory/my").unwrap_err(), StatusCode::UNAUTHORIZED);
+    }
+}
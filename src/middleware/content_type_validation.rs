@@ -40,6 +40,8 @@ use axum::{
     response::{IntoResponse, Response},
 };
 
+use super::problem_details::Problem;
+
 /// Validate Content-Type header for state-changing requests
 ///
 /// # Rules:
@@ -51,7 +53,7 @@ use axum::{
 pub async fn content_type_validation_middleware(
     request: Request,
     next: Next,
-) -> Result<Response, (StatusCode, String)> {
+) -> Result<Response, Problem> {
     let method = request.method();
     let path = request.uri().path();
 
@@ -80,10 +82,10 @@ pub async fn content_type_validation_middleware(
                         crate::utils::log_sanitizer::sanitize_for_log(ct)
                     );
 
-                    Err((
-                        StatusCode::UNSUPPORTED_MEDIA_TYPE,
-                        format!("Unsupported Content-Type: {}. Expected: application/json or multipart/form-data", ct),
-                    ))
+                    Err(Problem::new(StatusCode::UNSUPPORTED_MEDIA_TYPE, "unsupported_media_type").with_detail(format!(
+                        "Unsupported Content-Type: {}. Expected: application/json or multipart/form-data",
+                        ct
+                    )))
                 }
             }
             None => {
@@ -93,10 +95,7 @@ pub async fn content_type_validation_middleware(
                     crate::utils::log_sanitizer::sanitize_for_log(path)
                 );
 
-                Err((
-                    StatusCode::BAD_REQUEST,
-                    "Content-Type header required for this request".to_string(),
-                ))
+                Err(Problem::new(StatusCode::BAD_REQUEST, "missing_content_type"))
             }
         }
     } else {
@@ -115,12 +114,17 @@ fn is_valid_content_type(content_type: &str, path: &str) -> bool {
         .to_lowercase();
 
     // Multipart endpoints (file uploads)
-    if path.contains("/upload") || path.contains("/import") {
+    if path.contains("/upload") || path.contains("/import") || path.ends_with("/images") {
         // Accept both multipart and JSON (some upload endpoints accept JSON)
         return ct_base.starts_with("multipart/form-data") ||
                ct_base == "application/json";
     }
 
+    // OAuth token endpoints take form-encoded bodies (RFC 6749 §4.4.2)
+    if path == "/fhir/auth/token" {
+        return ct_base == "application/x-www-form-urlencoded";
+    }
+
     // All other endpoints should be JSON
     ct_base == "application/json"
 }
@@ -139,6 +143,7 @@ mod tests {
     fn test_valid_multipart_content_type() {
         assert!(is_valid_content_type("multipart/form-data", "/api/upload"));
         assert!(is_valid_content_type("multipart/form-data; boundary=----", "/api/import"));
+        assert!(is_valid_content_type("multipart/form-data", "/api/pharmaceuticals/123/images"));
     }
 
     #[test]
@@ -153,4 +158,11 @@ mod tests {
         assert!(is_valid_content_type("application/json", "/api/upload"));
         assert!(is_valid_content_type("multipart/form-data", "/api/upload"));
     }
+
+    #[test]
+    fn test_token_endpoint_requires_form() {
+        assert!(is_valid_content_type("application/x-www-form-urlencoded", "/fhir/auth/token"));
+        assert!(!is_valid_content_type("application/json", "/fhir/auth/token"));
+        assert!(!is_valid_content_type("application/x-www-form-urlencoded", "/api/auth/login"));
+    }
 }
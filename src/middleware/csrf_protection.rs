@@ -46,6 +46,7 @@ use axum::{
     middleware::Next,
     response::Response,
 };
+use super::problem_details::Problem;
 use rand::{thread_rng, Rng};
 use base64::{Engine as _, engine::general_purpose};
 
@@ -119,7 +120,7 @@ pub async fn csrf_protection_middleware(
     headers: HeaderMap,
     request: Request,
     next: Next,
-) -> Result<Response, (StatusCode, String)> {
+) -> Result<Response, Problem> {
     let method = request.method();
     let path = request.uri().path();
 
@@ -145,10 +146,7 @@ pub async fn csrf_protection_middleware(
     let cookie_token = extract_csrf_cookie(&headers)
         .ok_or_else(|| {
             tracing::warn!("⚠️  CSRF: Missing csrf-token cookie for {} {}", method, path);
-            (
-                StatusCode::FORBIDDEN,
-                "CSRF token missing in cookie".to_string(),
-            )
+            Problem::new(StatusCode::FORBIDDEN, "csrf_failed").with_detail("CSRF token missing in cookie")
         })?;
 
     // Extract CSRF token from header
@@ -157,10 +155,7 @@ pub async fn csrf_protection_middleware(
         .and_then(|v| v.to_str().ok())
         .ok_or_else(|| {
             tracing::warn!("⚠️  CSRF: Missing X-CSRF-Token header for {} {}", method, path);
-            (
-                StatusCode::FORBIDDEN,
-                "CSRF token missing in header".to_string(),
-            )
+            Problem::new(StatusCode::FORBIDDEN, "csrf_failed").with_detail("CSRF token missing in header")
         })?;
 
     // Validate tokens match
@@ -170,10 +165,7 @@ pub async fn csrf_protection_middleware(
             method,
             crate::utils::log_sanitizer::sanitize_for_log(path)
         );
-        return Err((
-            StatusCode::FORBIDDEN,
-            "CSRF token validation failed".to_string(),
-        ));
+        return Err(Problem::new(StatusCode::FORBIDDEN, "csrf_failed").with_detail("CSRF token validation failed"));
     }
 
     // ✅ CSRF token valid
@@ -43,12 +43,13 @@ use axum::{
     extract::rejection::JsonRejection,
     http::StatusCode,
     response::{IntoResponse, Response},
-    Json,
 };
 use serde_json::json;
 use thiserror::Error;
 use validator::ValidationErrors;
 
+use super::problem_details::Problem;
+
 #[derive(Error, Debug)]
 pub enum AppError {
     #[error("Database error: {0}")]
@@ -84,6 +85,10 @@ pub enum AppError {
     #[error("Conflict")]
     Conflict,
 
+    /// 409 for work of the same kind that is already running, with a message saying which
+    #[error("In progress: {0}")]
+    InProgress(String),
+
     #[error("Invalid input: {0}")]
     InvalidInput(String),
 
@@ -98,6 +103,9 @@ pub enum AppError {
 
     #[error("Encryption error: {0}")]
     Encryption(String),
+
+    #[error("AI output invalid: {0}")]
+    AiOutputInvalid(crate::services::ai_output_validation::AiOutputError),
 }
 
 impl From<crate::services::encryption_service::EncryptionError> for AppError {
@@ -108,9 +116,17 @@ impl From<crate::services::encryption_service::EncryptionError> for AppError {
     }
 }
 
-impl IntoResponse for AppError {
-    fn into_response(self) -> Response {
-        let (status, error_message) = match self {
+impl AppError {
+    /// HTTP status and client-safe message of the error. Details of server-side errors
+    /// are logged here and never part of the message.
+    pub fn client_error(&self) -> (StatusCode, String) {
+        match self {
+            AppError::Database(sqlx::Error::PoolTimedOut) => {
+                // Every connection stayed busy for the acquire timeout: the pool is exhausted
+                tracing::warn!("Database pool exhausted: timed out acquiring a connection");
+                super::metrics::record_db_pool_acquire_timeout();
+                (StatusCode::SERVICE_UNAVAILABLE, "Service is busy, please try again shortly".to_string())
+            }
             AppError::Database(err) => {
                 // 🔒 SECURITY: Log detailed database error server-side only
                 tracing::error!("Database error: {:?}", err);
@@ -118,29 +134,30 @@ impl IntoResponse for AppError {
             }
             AppError::Validation(_) => (StatusCode::BAD_REQUEST, "Validation failed".to_string()),
             AppError::Json(_) => (StatusCode::BAD_REQUEST, "Invalid JSON".to_string()),
-            AppError::JsonParsing(ref e) => {
+            AppError::JsonParsing(e) => {
                 // 🔒 SECURITY: Log detailed JSON parsing error server-side, return generic message to client
                 tracing::error!("JSON parsing error: {:?}", e);
                 (StatusCode::BAD_REQUEST, "Invalid JSON format".to_string())
             }
-            AppError::Jwt(ref e) => {
+            AppError::Jwt(e) => {
                 // 🔒 SECURITY: Log detailed JWT error server-side, return generic message to client
                 tracing::error!("JWT error: {:?}", e);
                 (StatusCode::UNAUTHORIZED, "Invalid token".to_string())
             }
-            AppError::PasswordHash(ref e) => {
+            AppError::PasswordHash(e) => {
                 // 🔒 SECURITY: Log detailed password hashing error server-side only
                 tracing::error!("Password hashing error: {:?}", e);
                 (StatusCode::INTERNAL_SERVER_ERROR, "Password processing error".to_string())
             }
-            AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
+            AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg.clone()),
             AppError::Unauthorized => (StatusCode::UNAUTHORIZED, "Unauthorized".to_string()),
-            AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
-            AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
+            AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg.clone()),
+            AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg.clone()),
             AppError::Conflict => (StatusCode::CONFLICT, "Resource already exists".to_string()),
+            AppError::InProgress(msg) => (StatusCode::CONFLICT, msg.clone()),
             AppError::InvalidInput(msg) => (StatusCode::BAD_REQUEST, msg.clone()),
-            AppError::QuotaExceeded(msg) => (StatusCode::TOO_MANY_REQUESTS, msg),
-            AppError::TooManyRequests(msg) => (StatusCode::TOO_MANY_REQUESTS, msg),
+            AppError::QuotaExceeded(msg) => (StatusCode::TOO_MANY_REQUESTS, msg.clone()),
+            AppError::TooManyRequests(msg) => (StatusCode::TOO_MANY_REQUESTS, msg.clone()),
             AppError::Internal(err) => {
                 // 🔒 SECURITY: Log detailed internal error server-side only
                 tracing::error!("Internal error: {:?}", err);
@@ -151,14 +168,71 @@ impl IntoResponse for AppError {
                 // Note: Detailed error is logged when the error is created (see From impl above)
                 (StatusCode::INTERNAL_SERVER_ERROR, "Encryption error".to_string())
             }
-        };
+            AppError::AiOutputInvalid(err) => {
+                tracing::error!("{}", err);
+                (StatusCode::BAD_GATEWAY, AI_OUTPUT_INVALID_MESSAGE.to_string())
+            }
+        }
+    }
+}
+
+impl AppError {
+    /// Stable, machine-readable code of the error (the `code` of its problem document)
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::Database(sqlx::Error::PoolTimedOut) => "service_busy",
+            AppError::Validation(_) => "validation_failed",
+            AppError::Json(_) | AppError::JsonParsing(_) => "invalid_json",
+            AppError::Jwt(_) => "invalid_token",
+            AppError::NotFound(_) => "not_found",
+            AppError::Unauthorized => "unauthorized",
+            AppError::Forbidden(_) => "forbidden",
+            AppError::BadRequest(_) => "bad_request",
+            AppError::Conflict | AppError::InProgress(_) => "conflict",
+            AppError::InvalidInput(_) => "invalid_input",
+            AppError::QuotaExceeded(_) => "quota_exceeded",
+            AppError::TooManyRequests(_) => "rate_limited",
+            AppError::AiOutputInvalid(_) => "ai_output_invalid",
+            AppError::Database(_) | AppError::PasswordHash(_) | AppError::Internal(_) | AppError::Encryption(_) => {
+                "internal_error"
+            }
+        }
+    }
 
-        let body = Json(json!({
-            "error": error_message,
-            "status": status.as_u16()
-        }));
+    /// The error as a problem document. Only developer-controlled messages become
+    /// its detail; every other error is described by its (localized) title alone.
+    pub fn problem(&self) -> Problem {
+        let (status, message) = self.client_error();
+        let problem = Problem::new(status, self.code());
+        match self {
+            AppError::Validation(errors) => problem.with_field_errors(errors),
+            AppError::NotFound(_)
+            | AppError::Forbidden(_)
+            | AppError::BadRequest(_)
+            | AppError::InProgress(_)
+            | AppError::InvalidInput(_)
+            | AppError::QuotaExceeded(_)
+            | AppError::TooManyRequests(_) => problem.with_detail(message),
+            // Validation errors describe the model's output, not internal state, so they are
+            // returned to let clients tell a bad AI answer apart from a server fault
+            AppError::AiOutputInvalid(err) => problem
+                .with_detail(message)
+                .with_extension("feature", json!(err.feature.as_str()))
+                .with_extension("validation_errors", json!(err.errors)),
+            _ => problem,
+        }
+    }
+}
+
+const AI_OUTPUT_INVALID_MESSAGE: &str = "The AI service returned a response that could not be validated. Please try again.";
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        // 📊 Server errors go to the error tracker (no-op when tracking is off)
+        super::error_tracking::capture_app_error(&self);
 
-        (status, body).into_response()
+        // 🔒 SECURITY: `problem` logs server-side details and returns client-safe text only
+        self.problem().into_response()
     }
 }
 
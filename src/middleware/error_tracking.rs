@@ -0,0 +1,338 @@
+// ============================================================================
+// Error Tracking - Server Errors and Panics to a Sentry-Compatible Backend
+// ============================================================================
+//
+// 📊 OBSERVABILITY: Every 5xx `AppError` and every panic is sent to the error
+// tracker (Sentry, or a compatible backend such as GlitchTip or a self-hosted
+// Sentry) with:
+//
+// - release (`atlas-pharma@<version>` unless SENTRY_RELEASE is set) and environment
+// - request context: request_id, method and route template as tags, the route as
+//   the transaction (never the raw path, so IDs and query strings stay out)
+// - user: the authenticated user's id only (see `set_request_user`)
+// - a fingerprint that groups errors by cause rather than by message, so one
+//   broken ERP connection or AI outage is one issue, not thousands:
+//   - sqlx errors: error kind, plus SQLSTATE and constraint for database errors
+//   - AI errors: provider/feature and the normalized error context
+//   - ERP errors: the ERP system and the normalized error context
+//
+// Client errors (4xx) are never reported.
+//
+// ## Configuration:
+//
+// - SENTRY_DSN: where events are sent; tracking is off without it
+// - ERROR_TRACKING_ENABLED: `false` switches tracking off even with a DSN, for
+//   on-prem installs that must not send anything off site
+// - SENTRY_ENVIRONMENT: environment tag (production)
+// - SENTRY_RELEASE: release tag override (e.g. the deployed git sha)
+//
+// ## Redaction:
+//
+// Error messages pass through `log_sanitizer::redact_pii` before sending, and
+// request headers and bodies are never attached.
+//
+// ============================================================================
+
+use axum::{
+    extract::{MatchedPath, Request},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use sentry::{protocol::Event, Hub, SentryFutureExt};
+use std::{any::Any, borrow::Cow, sync::Arc};
+use uuid::Uuid;
+
+use super::{error_handling::AppError, problem_details::Problem, request_id::get_request_id};
+use crate::utils::log_sanitizer::redact_pii;
+
+/// Words in an error message that identify the ERP system involved
+const ERP_SYSTEMS: &[&str] = &["netsuite", "sap", "sftp", "edi", "as2"];
+
+/// Words in an error message that mark it as an AI provider error
+const AI_MARKERS: &[&str] = &["claude", "anthropic", "embedding", "embeddings"];
+
+/// Longest normalized message kept in a fingerprint
+const MAX_TEMPLATE_LEN: usize = 100;
+
+#[derive(Debug, Clone)]
+pub struct ErrorTrackingConfig {
+    pub dsn: Option<String>,
+    pub enabled: bool,
+    pub environment: String,
+    pub release: String,
+}
+
+impl ErrorTrackingConfig {
+    pub fn from_env() -> Self {
+        Self {
+            dsn: std::env::var("SENTRY_DSN").ok().filter(|dsn| !dsn.trim().is_empty()),
+            enabled: std::env::var("ERROR_TRACKING_ENABLED")
+                .map(|enabled| !matches!(enabled.trim().to_lowercase().as_str(), "false" | "0" | "off"))
+                .unwrap_or(true),
+            environment: std::env::var("SENTRY_ENVIRONMENT").unwrap_or_else(|_| "production".to_string()),
+            release: std::env::var("SENTRY_RELEASE")
+                .unwrap_or_else(|_| format!("{}@{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))),
+        }
+    }
+
+    /// The DSN to send to, when tracking is on
+    pub fn active_dsn(&self) -> Option<&str> {
+        self.dsn.as_deref().filter(|_| self.enabled)
+    }
+}
+
+/// Start the error tracking client and its panic hook
+///
+/// Call once at startup and keep the guard for the life of the process; dropping
+/// it flushes queued events. Returns None when tracking is off.
+pub fn init() -> Option<sentry::ClientInitGuard> {
+    let config = ErrorTrackingConfig::from_env();
+    let dsn = config.active_dsn()?;
+
+    let guard = sentry::init((
+        dsn,
+        sentry::ClientOptions {
+            release: Some(Cow::Owned(config.release.clone())),
+            environment: Some(Cow::Owned(config.environment.clone())),
+            send_default_pii: false,
+            before_send: Some(Arc::new(|event: Event<'static>| Some(redact_event(event)))),
+            ..Default::default()
+        },
+    ));
+
+    guard.is_enabled().then_some(guard)
+}
+
+/// Mask PII in the parts of an event that carry error text
+fn redact_event(mut event: Event<'static>) -> Event<'static> {
+    if let Some(message) = event.message.as_mut() {
+        *message = redact_pii(message).into_owned();
+    }
+    for exception in event.exception.values.iter_mut() {
+        if let Some(value) = exception.value.as_mut() {
+            *value = redact_pii(value).into_owned();
+        }
+    }
+    event
+}
+
+/// Report a server error; client errors are ignored
+pub fn capture_app_error(error: &AppError) {
+    if Hub::current().client().is_none() {
+        return;
+    }
+    let Some(fingerprint) = fingerprint(error) else {
+        return;
+    };
+
+    let mut event = sentry::event_from_error(error);
+    event.level = sentry::Level::Error;
+    event.tags.insert("error_kind".to_string(), fingerprint[0].clone());
+    event.fingerprint = Cow::Owned(fingerprint.into_iter().map(Cow::Owned).collect());
+    sentry::capture_event(event);
+}
+
+/// Grouping key of a server error, None for client errors
+pub fn fingerprint(error: &AppError) -> Option<Vec<String>> {
+    let fingerprint = match error {
+        AppError::Database(err) => sqlx_fingerprint(err),
+        AppError::Internal(err) => match err.chain().find_map(|cause| cause.downcast_ref::<sqlx::Error>()) {
+            Some(sqlx_err) => sqlx_fingerprint(sqlx_err),
+            None => message_fingerprint(&err.to_string()),
+        },
+        AppError::AiOutputInvalid(err) => vec![
+            "ai".to_string(),
+            "output_invalid".to_string(),
+            err.feature.as_str().to_string(),
+        ],
+        AppError::PasswordHash(_) => vec!["app".to_string(), "password_hash".to_string()],
+        AppError::Encryption(_) => vec!["app".to_string(), "encryption".to_string()],
+        _ => return None,
+    };
+    Some(fingerprint)
+}
+
+/// sqlx errors group by kind; database errors also by SQLSTATE and constraint,
+/// so each violated constraint is its own issue
+fn sqlx_fingerprint(error: &sqlx::Error) -> Vec<String> {
+    let kind = match error {
+        sqlx::Error::Database(db) => {
+            return vec![
+                "sqlx".to_string(),
+                "database".to_string(),
+                db.code().map(|code| code.into_owned()).unwrap_or_else(|| "unknown".to_string()),
+                db.constraint().unwrap_or("-").to_string(),
+            ];
+        }
+        sqlx::Error::RowNotFound => "row_not_found",
+        sqlx::Error::PoolTimedOut => "pool_timed_out",
+        sqlx::Error::PoolClosed => "pool_closed",
+        sqlx::Error::Io(_) => "io",
+        sqlx::Error::Tls(_) => "tls",
+        sqlx::Error::Protocol(_) => "protocol",
+        sqlx::Error::ColumnNotFound(_)
+        | sqlx::Error::ColumnIndexOutOfBounds { .. }
+        | sqlx::Error::ColumnDecode { .. }
+        | sqlx::Error::Decode(_)
+        | sqlx::Error::TypeNotFound { .. } => "decode",
+        sqlx::Error::Migrate(_) => "migrate",
+        _ => "other",
+    };
+    vec!["sqlx".to_string(), kind.to_string()]
+}
+
+/// Internal errors are `context: cause` strings; group AI and ERP errors by their
+/// subsystem and every error by its normalized context
+fn message_fingerprint(message: &str) -> Vec<String> {
+    let template = message_template(message);
+    let words: Vec<String> = message
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+
+    if let Some(system) = ERP_SYSTEMS.iter().find(|system| words.iter().any(|word| word == *system)) {
+        return vec!["erp".to_string(), system.to_string(), template];
+    }
+    if words.iter().any(|word| word == "erp") {
+        return vec!["erp".to_string(), "erp".to_string(), template];
+    }
+    if words.iter().any(|word| AI_MARKERS.contains(&word.as_str())) {
+        return vec!["ai".to_string(), template];
+    }
+    vec!["internal".to_string(), template]
+}
+
+/// The context part of a message (before the first `:`), lowercased, with digit
+/// runs collapsed so status codes and counts don't split issues
+fn message_template(message: &str) -> String {
+    let context = message.split(':').next().unwrap_or(message).trim().to_lowercase();
+    let mut template = String::with_capacity(context.len());
+    for c in context.chars() {
+        if c.is_ascii_digit() {
+            if !template.ends_with('#') {
+                template.push('#');
+            }
+        } else {
+            template.push(c);
+        }
+    }
+    template.chars().take(MAX_TEMPLATE_LEN).collect()
+}
+
+/// Record the authenticated user on the current request's error tracking scope
+///
+/// Only the id is sent; emails and names stay out of the tracker.
+pub fn set_request_user(user_id: Uuid) {
+    sentry::configure_scope(|scope| {
+        scope.set_user(Some(sentry::User {
+            id: Some(user_id.to_string()),
+            ..Default::default()
+        }));
+    });
+}
+
+/// Give each request its own error tracking scope carrying its context
+///
+/// Runs inside `request_id_middleware` so events and the request logs share the
+/// request id.
+pub async fn error_tracking_middleware(request: Request, next: Next) -> Response {
+    if Hub::current().client().is_none() {
+        return next.run(request).await;
+    }
+
+    let request_id = get_request_id(request.extensions()).unwrap_or_else(Uuid::new_v4);
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| super::metrics::normalize_path(request.uri().path()));
+    let method = request.method().to_string();
+
+    let hub = Arc::new(Hub::new_from_top(Hub::current()));
+    hub.configure_scope(|scope| {
+        scope.set_tag("request_id", request_id);
+        scope.set_tag("http.method", &method);
+        scope.set_tag("http.route", &route);
+        scope.set_transaction(Some(&format!("{} {}", method, route)));
+    });
+
+    next.run(request).bind_hub(hub).await
+}
+
+/// Response for a request whose handler panicked (the panic hook has already
+/// reported it)
+pub fn panic_response(panic: Box<dyn Any + Send + 'static>) -> Response {
+    let message = panic
+        .downcast_ref::<String>()
+        .map(String::as_str)
+        .or_else(|| panic.downcast_ref::<&str>().copied())
+        .unwrap_or("unknown panic");
+    tracing::error!("Request handler panicked: {}", message);
+
+    Problem::new(StatusCode::INTERNAL_SERVER_ERROR, "internal_error").into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_errors_not_reported() {
+        assert_eq!(fingerprint(&AppError::NotFound("Inventory not found".to_string())), None);
+        assert_eq!(fingerprint(&AppError::Forbidden("Account suspended".to_string())), None);
+        assert_eq!(fingerprint(&AppError::Unauthorized), None);
+        assert_eq!(fingerprint(&AppError::TooManyRequests("Slow down".to_string())), None);
+    }
+
+    #[test]
+    fn test_sqlx_errors_group_by_kind() {
+        assert_eq!(
+            fingerprint(&AppError::Database(sqlx::Error::PoolTimedOut)),
+            Some(vec!["sqlx".to_string(), "pool_timed_out".to_string()])
+        );
+        // Wrapped in anyhow context, still grouped as the sqlx error
+        let wrapped = anyhow::Error::new(sqlx::Error::RowNotFound).context("Failed to load connection");
+        assert_eq!(
+            fingerprint(&AppError::Internal(wrapped)),
+            Some(vec!["sqlx".to_string(), "row_not_found".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_erp_and_ai_errors_group_by_subsystem() {
+        let netsuite_500 = AppError::Internal(anyhow::anyhow!("NetSuite API error (500): Internal error for item 4471"));
+        let netsuite_503 = AppError::Internal(anyhow::anyhow!("NetSuite API error (503): Service unavailable"));
+        assert_eq!(fingerprint(&netsuite_500), fingerprint(&netsuite_503));
+        assert_eq!(
+            fingerprint(&netsuite_500),
+            Some(vec!["erp".to_string(), "netsuite".to_string(), "netsuite api error (#)".to_string()])
+        );
+
+        let erp_write = AppError::Internal(anyhow::anyhow!("Failed to write to ERP: connection reset"));
+        assert_eq!(fingerprint(&erp_write).unwrap()[..2], ["erp".to_string(), "erp".to_string()]);
+
+        let claude = AppError::Internal(anyhow::anyhow!("Claude API request failed: timed out"));
+        assert_eq!(
+            fingerprint(&claude),
+            Some(vec!["ai".to_string(), "claude api request failed".to_string()])
+        );
+
+        let other = AppError::Internal(anyhow::anyhow!("Report generation failed: disk full"));
+        assert_eq!(fingerprint(&other).unwrap()[0], "internal");
+    }
+
+    #[test]
+    fn test_disabled_switch() {
+        let config = ErrorTrackingConfig {
+            dsn: Some("https://key@errors.example.com/1".to_string()),
+            enabled: false,
+            environment: "production".to_string(),
+            release: "atlas-pharma@0.1.0".to_string(),
+        };
+        assert_eq!(config.active_dsn(), None);
+        assert_eq!(ErrorTrackingConfig { enabled: true, ..config.clone() }.active_dsn(), config.dsn.as_deref());
+        assert_eq!(ErrorTrackingConfig { enabled: true, dsn: None, ..config }.active_dsn(), None);
+    }
+}
@@ -0,0 +1,290 @@
+// ============================================================================
+// Field Selection - Sparse Fieldsets and Expansion of Related Resources
+// ============================================================================
+//
+// `?fields=` trims any JSON response to the listed members, so mobile clients
+// don't download catalog JSON they never show:
+//
+//   GET /api/inventory/my?fields=id,quantity,pharmaceutical.brand_name
+//
+// - Paths are comma-separated; a dotted path selects a member of a nested
+//   object, and a path to an object keeps all of it
+// - Arrays are transparent: the selection applies to each element, so it works
+//   for lists and for envelopes alike (`fields=results.id,total`)
+// - Members that don't exist are ignored; a malformed path is a 400
+// - Only successful `application/json` responses are trimmed; errors, files and
+//   other formats (FHIR, NDJSON) pass through
+//
+// `?expand=` embeds related resources a response leaves out by default. Each
+// endpoint declares the paths it supports (`ExpandQuery::expansions`) and
+// rejects others:
+//
+//   GET /api/inventory/:id?expand=pharmaceutical.catalog_entry
+//   GET /api/pharmaceuticals/:id?expand=catalog_entry
+//
+// ============================================================================
+
+use axum::{
+    body::{to_bytes, Body, HttpBody},
+    extract::Request,
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use utoipa::IntoParams;
+
+use super::{error_handling::AppError, problem_details::Problem};
+
+/// Paths a single `fields` parameter may list
+const MAX_FIELD_PATHS: usize = 100;
+
+/// Nesting depth of a field path
+const MAX_FIELD_DEPTH: usize = 5;
+
+/// Largest response body that is trimmed; bigger ones are returned in full
+const MAX_SELECTED_BODY_BYTES: u64 = 32 * 1024 * 1024;
+
+/// Members to keep of a JSON value; no children keeps the whole value
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct FieldSelection {
+    children: BTreeMap<String, FieldSelection>,
+}
+
+impl FieldSelection {
+    /// Parse a `fields` parameter (`id,pharmaceutical.brand_name`)
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let paths: Vec<&str> = value.split(',').map(str::trim).filter(|path| !path.is_empty()).collect();
+        if paths.is_empty() {
+            return Err("fields must list at least one field".to_string());
+        }
+        if paths.len() > MAX_FIELD_PATHS {
+            return Err(format!("fields may list at most {} fields", MAX_FIELD_PATHS));
+        }
+
+        let mut selection = FieldSelection::default();
+        for path in paths {
+            let segments: Vec<&str> = path.split('.').collect();
+            if segments.len() > MAX_FIELD_DEPTH {
+                return Err(format!("Field '{}' is nested more than {} levels deep", path, MAX_FIELD_DEPTH));
+            }
+            if !segments.iter().all(|segment| is_field_name(segment)) {
+                return Err(format!("Invalid field '{}'", path));
+            }
+            selection.insert(&segments);
+        }
+        Ok(selection)
+    }
+
+    fn insert(&mut self, segments: &[&str]) {
+        let Some((first, rest)) = segments.split_first() else { return };
+        // A path already kept whole stays whole
+        if let Some(child) = self.children.get(*first) {
+            if child.children.is_empty() {
+                return;
+            }
+        }
+        let child = self.children.entry(first.to_string()).or_default();
+        if rest.is_empty() {
+            child.children.clear();
+        } else {
+            child.insert(rest);
+        }
+    }
+
+    /// Drop every member of `value` that isn't selected
+    pub fn apply(&self, value: &mut Value) {
+        if self.children.is_empty() {
+            return;
+        }
+        match value {
+            Value::Object(members) => {
+                members.retain(|name, member| match self.children.get(name) {
+                    Some(child) => {
+                        child.apply(member);
+                        true
+                    }
+                    None => false,
+                });
+            }
+            Value::Array(elements) => elements.iter_mut().for_each(|element| self.apply(element)),
+            _ => {}
+        }
+    }
+}
+
+fn is_field_name(segment: &str) -> bool {
+    !segment.is_empty() && segment.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Value of the `fields` query parameter, if the request has one
+fn fields_parameter(request: &Request) -> Option<String> {
+    let query = request.uri().query()?;
+    url::form_urlencoded::parse(query.as_bytes())
+        .find(|(name, _)| name == "fields")
+        .map(|(_, value)| value.into_owned())
+}
+
+fn is_json(response: &Response) -> bool {
+    response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(|content_type| content_type.trim().to_lowercase().starts_with("application/json"))
+        .unwrap_or(false)
+}
+
+/// Trim successful JSON responses of requests with `?fields=` to the selected members
+pub async fn field_selection_middleware(request: Request, next: Next) -> Response {
+    let Some(fields) = fields_parameter(&request) else {
+        return next.run(request).await;
+    };
+    let selection = match FieldSelection::parse(&fields) {
+        Ok(selection) => selection,
+        Err(message) => return Problem::new(StatusCode::BAD_REQUEST, "bad_request").with_detail(message).into_response(),
+    };
+
+    let response = next.run(request).await;
+    if !response.status().is_success() || !is_json(&response) {
+        return response;
+    }
+    match response.body().size_hint().upper() {
+        Some(size) if size <= MAX_SELECTED_BODY_BYTES => {}
+        _ => return response,
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, MAX_SELECTED_BODY_BYTES as usize).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::error!("Failed to read response body for field selection: {}", e);
+            return AppError::Internal(anyhow::anyhow!("Failed to read response body")).into_response();
+        }
+    };
+    let mut value: Value = match serde_json::from_slice(&bytes) {
+        Ok(value) => value,
+        Err(_) => return Response::from_parts(parts, Body::from(bytes)),
+    };
+
+    selection.apply(&mut value);
+    let trimmed = serde_json::to_vec(&value).unwrap_or_else(|_| bytes.to_vec());
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(trimmed))
+}
+
+/// `?expand=`: related resources to embed, comma-separated
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExpandQuery {
+    /// Related resources to embed, comma-separated (e.g. `pharmaceutical.catalog_entry`)
+    pub expand: Option<String>,
+}
+
+impl ExpandQuery {
+    /// The requested expansions; anything the endpoint doesn't support is a 400
+    pub fn expansions(&self, supported: &[&'static str]) -> Result<Expansions, AppError> {
+        let mut expansions = Vec::new();
+        for path in self.expand.as_deref().unwrap_or_default().split(',').map(str::trim) {
+            if path.is_empty() {
+                continue;
+            }
+            match supported.iter().find(|known| **known == path) {
+                Some(known) => expansions.push(*known),
+                None => {
+                    return Err(AppError::BadRequest(format!(
+                        "Cannot expand '{}'; supported: {}",
+                        path,
+                        supported.join(", ")
+                    )))
+                }
+            }
+        }
+        Ok(Expansions(expansions))
+    }
+}
+
+/// Expansions requested of an endpoint
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Expansions(Vec<&'static str>);
+
+impl Expansions {
+    pub fn contains(&self, path: &str) -> bool {
+        self.0.iter().any(|expansion| *expansion == path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::Request, middleware, routing::get, Json, Router};
+    use serde_json::json;
+    use tower::ServiceExt;
+
+    #[test]
+    fn test_parse_merges_paths() {
+        let selection = FieldSelection::parse("id, pharmaceutical.brand_name,pharmaceutical").unwrap();
+        assert_eq!(selection, FieldSelection::parse("pharmaceutical,id").unwrap());
+
+        assert!(FieldSelection::parse(" , ").is_err());
+        assert!(FieldSelection::parse("id,pharmaceutical..brand_name").is_err());
+        assert!(FieldSelection::parse("a.b.c.d.e.f").is_err());
+        assert!(FieldSelection::parse("name;drop").is_err());
+    }
+
+    #[test]
+    fn test_apply_keeps_selected_members() {
+        let selection = FieldSelection::parse("results.id,results.pharmaceutical.brand_name,total").unwrap();
+        let mut value = json!({
+            "results": [
+                { "id": 1, "quantity": 5, "pharmaceutical": { "brand_name": "Lipitor", "epi_data": { "big": true } } },
+                { "id": 2, "quantity": 7, "pharmaceutical": null },
+            ],
+            "total": 2,
+            "facets": {},
+        });
+        selection.apply(&mut value);
+        assert_eq!(
+            value,
+            json!({
+                "results": [
+                    { "id": 1, "pharmaceutical": { "brand_name": "Lipitor" } },
+                    { "id": 2, "pharmaceutical": null },
+                ],
+                "total": 2,
+            })
+        );
+    }
+
+    #[test]
+    fn test_expansions() {
+        let query = ExpandQuery { expand: Some("pharmaceutical.catalog_entry,".to_string()) };
+        let expansions = query.expansions(&["pharmaceutical.catalog_entry"]).unwrap();
+        assert!(expansions.contains("pharmaceutical.catalog_entry"));
+
+        assert_eq!(ExpandQuery::default().expansions(&[]).unwrap(), Expansions::default());
+        assert!(query.expansions(&["catalog_entry"]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_middleware_trims_json_responses() {
+        let app = Router::new()
+            .route("/item", get(|| async { Json(json!({ "id": 1, "metadata": { "large": true } })) }))
+            .layer(middleware::from_fn(field_selection_middleware));
+
+        let response = app
+            .clone()
+            .oneshot(Request::builder().uri("/item?fields=id").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(serde_json::from_slice::<Value>(&body).unwrap(), json!({ "id": 1 }));
+
+        let response = app
+            .oneshot(Request::builder().uri("/item?fields=id..x").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
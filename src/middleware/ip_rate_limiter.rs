@@ -6,21 +6,41 @@
 /// - Per-IP tracking with automatic cleanup
 ///
 /// Industry standard: Redis-backed for distributed systems, in-memory for single instance
+///
+/// Every response of a rate-limited route carries the caller's budget (the tightest
+/// one when several limiters apply, e.g. `auth` inside `api`):
+/// - X-RateLimit-Limit: requests allowed per window
+/// - X-RateLimit-Remaining: requests left in the current window
+/// - X-RateLimit-Reset: Unix time (seconds) when the oldest counted request leaves the window
 
 use axum::{
     extract::{ConnectInfo, Request},
-    http::StatusCode,
+    http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode},
     middleware::Next,
     response::{IntoResponse, Response},
 };
+use chrono::{DateTime, Utc};
 use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use utoipa::ToSchema;
 use std::{
     net::SocketAddr,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, RwLock, Weak,
+    },
     time::{Duration, Instant},
 };
 use tokio::time::sleep;
 
+pub const RATE_LIMIT_LIMIT_HEADER: &str = "x-ratelimit-limit";
+pub const RATE_LIMIT_REMAINING_HEADER: &str = "x-ratelimit-remaining";
+pub const RATE_LIMIT_RESET_HEADER: &str = "x-ratelimit-reset";
+
+/// Limiters created with `RateLimiter::named`, reported on the admin ops dashboard
+static NAMED_LIMITERS: Lazy<Mutex<Vec<Weak<RateLimiter>>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
 /// Rate limiter configuration
 #[derive(Clone)]
 pub struct RateLimitConfig {
@@ -116,37 +136,110 @@ impl IpTracker {
         true
     }
 
-    /// Get retry-after seconds
-    fn retry_after(&self, config: &RateLimitConfig) -> u64 {
-        if let Some(&oldest) = self.requests.first() {
-            let elapsed = Instant::now().duration_since(oldest);
-            let remaining = config.window.saturating_sub(elapsed);
-            remaining.as_secs()
-        } else {
-            0
+    /// Requests inside the current window, without recording a new one
+    fn in_window(&self, config: &RateLimitConfig, now: Instant) -> usize {
+        self.requests
+            .iter()
+            .filter(|&&req_time| now.duration_since(req_time) < config.window)
+            .count()
+    }
+
+    /// Time until the oldest request inside the window leaves it
+    fn reset_after(&self, config: &RateLimitConfig, now: Instant) -> Duration {
+        self.requests
+            .iter()
+            .find(|&&req_time| now.duration_since(req_time) < config.window)
+            .map(|&oldest| config.window.saturating_sub(now.duration_since(oldest)))
+            .unwrap_or_default()
+    }
+}
+
+/// One caller's budget with one limiter
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct RateLimitStatus {
+    /// `api` applies to every route, `auth` to sign-in and token endpoints
+    #[schema(value_type = String)]
+    pub name: &'static str,
+    pub limit: u32,
+    pub remaining: u32,
+    pub window_seconds: u64,
+    /// Seconds until the oldest counted request leaves the window (the Retry-After of a 429)
+    pub reset_seconds: u64,
+    pub reset_at: DateTime<Utc>,
+}
+
+impl RateLimitStatus {
+    fn new(name: &'static str, config: &RateLimitConfig, used: usize, reset_after: Duration) -> Self {
+        // Round up: a client retrying after `reset_seconds` must not be rejected again
+        let reset_seconds = reset_after.as_secs() + u64::from(reset_after.subsec_nanos() > 0);
+        Self {
+            name,
+            limit: config.max_requests,
+            remaining: config.max_requests.saturating_sub(used as u32),
+            window_seconds: config.window.as_secs(),
+            reset_seconds,
+            reset_at: Utc::now() + chrono::Duration::seconds(reset_seconds as i64),
         }
     }
+
+    /// Set the X-RateLimit-* headers, unless an inner limiter already set a tighter budget
+    pub fn apply_to(&self, headers: &mut HeaderMap) {
+        let current_remaining = headers
+            .get(RATE_LIMIT_REMAINING_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<u32>().ok());
+        if current_remaining.is_some_and(|remaining| remaining <= self.remaining) {
+            return;
+        }
+
+        headers.insert(HeaderName::from_static(RATE_LIMIT_LIMIT_HEADER), HeaderValue::from(self.limit));
+        headers.insert(HeaderName::from_static(RATE_LIMIT_REMAINING_HEADER), HeaderValue::from(self.remaining));
+        headers.insert(HeaderName::from_static(RATE_LIMIT_RESET_HEADER), HeaderValue::from(self.reset_at.timestamp()));
+    }
+}
+
+/// Current load of one rate limiter in this process
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct RateLimiterSnapshot {
+    #[schema(value_type = String)]
+    pub name: &'static str,
+    pub max_requests: u32,
+    pub window_seconds: u64,
+    /// IPs with requests inside the current window
+    pub active_ips: usize,
+    /// IPs that used up their limit and are being rejected
+    pub saturated_ips: usize,
+    /// Highest share of the limit used by a single IP, in percent
+    pub peak_usage_percent: f64,
+    /// Requests rejected since the process started
+    pub rejected_total: u64,
 }
 
 /// Production rate limiter with automatic cleanup
 pub struct RateLimiter {
+    name: &'static str,
     trackers: Arc<DashMap<String, IpTracker>>,
-    config: RateLimitConfig,
+    /// Replaced at runtime by `reconfigure` (runtime settings)
+    config: Arc<RwLock<RateLimitConfig>>,
+    rejected: AtomicU64,
 }
 
 impl RateLimiter {
     pub fn new(config: RateLimitConfig) -> Self {
-        let window = config.window; // Clone before moving into Self
         let limiter = Self {
+            name: "unnamed",
             trackers: Arc::new(DashMap::new()),
-            config,
+            config: Arc::new(RwLock::new(config)),
+            rejected: AtomicU64::new(0),
         };
 
         // Spawn cleanup task
         let trackers = limiter.trackers.clone();
+        let config = limiter.config.clone();
         tokio::spawn(async move {
             loop {
                 sleep(Duration::from_secs(300)).await; // Cleanup every 5 minutes
+                let window = config.read().unwrap_or_else(|e| e.into_inner()).window;
                 trackers.retain(|_, tracker| {
                     // Remove trackers with no recent activity
                     Instant::now().duration_since(tracker.last_cleanup) < window * 2
@@ -157,16 +250,115 @@ impl RateLimiter {
         limiter
     }
 
-    /// Check if request is allowed
+    /// A limiter that shows up under `name` in `RateLimiter::snapshots`
+    pub fn named(name: &'static str, config: RateLimitConfig) -> Arc<Self> {
+        let limiter = Arc::new(Self { name, ..Self::new(config) });
+
+        let mut registry = NAMED_LIMITERS.lock().unwrap_or_else(|e| e.into_inner());
+        registry.retain(|limiter| limiter.strong_count() > 0);
+        registry.push(Arc::downgrade(&limiter));
+
+        limiter
+    }
+
+    /// Apply `config` to every named limiter called `name`; returns how many changed
+    pub fn reconfigure(name: &str, config: RateLimitConfig) -> usize {
+        let registry = NAMED_LIMITERS.lock().unwrap_or_else(|e| e.into_inner());
+        let limiters: Vec<Arc<RateLimiter>> = registry
+            .iter()
+            .filter_map(Weak::upgrade)
+            .filter(|limiter| limiter.name == name)
+            .collect();
+
+        for limiter in &limiters {
+            limiter.set_config(config.clone());
+        }
+        limiters.len()
+    }
+
+    /// The limits currently in force
+    pub fn config(&self) -> RateLimitConfig {
+        self.config.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Replace the limits; requests already counted stay in their window
+    pub fn set_config(&self, config: RateLimitConfig) {
+        *self.config.write().unwrap_or_else(|e| e.into_inner()) = config;
+    }
+
+    /// Snapshots of every named limiter that is still in use
+    pub fn snapshots() -> Vec<RateLimiterSnapshot> {
+        let registry = NAMED_LIMITERS.lock().unwrap_or_else(|e| e.into_inner());
+        registry.iter().filter_map(Weak::upgrade).map(|limiter| limiter.snapshot()).collect()
+    }
+
+    pub fn snapshot(&self) -> RateLimiterSnapshot {
+        let now = Instant::now();
+        let config = self.config();
+        let max_requests = config.max_requests.max(1) as usize;
+
+        let (mut active_ips, mut saturated_ips, mut peak) = (0, 0, 0);
+        for tracker in self.trackers.iter() {
+            let count = tracker.in_window(&config, now);
+            if count == 0 {
+                continue;
+            }
+            active_ips += 1;
+            if count >= max_requests {
+                saturated_ips += 1;
+            }
+            peak = peak.max(count);
+        }
+
+        RateLimiterSnapshot {
+            name: self.name,
+            max_requests: config.max_requests,
+            window_seconds: config.window.as_secs(),
+            active_ips,
+            saturated_ips,
+            peak_usage_percent: (peak as f64 / max_requests as f64 * 1000.0).round() / 10.0,
+            rejected_total: self.rejected.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Check if request is allowed; the error is the retry-after in seconds
     pub fn check(&self, ip: &str) -> Result<(), u64> {
+        self.acquire(ip).map(|_| ()).map_err(|status| status.reset_seconds)
+    }
+
+    /// Count a request; the caller's budget afterwards, or when it was rejected
+    pub fn acquire(&self, ip: &str) -> Result<RateLimitStatus, RateLimitStatus> {
+        let config = self.config();
         let mut entry = self.trackers.entry(ip.to_string()).or_insert_with(IpTracker::new);
 
-        if entry.check_limit(&self.config) {
-            Ok(())
+        let allowed = entry.check_limit(&config);
+        let now = Instant::now();
+        let status = RateLimitStatus::new(self.name, &config, entry.in_window(&config, now), entry.reset_after(&config, now));
+        if allowed {
+            Ok(status)
         } else {
-            Err(entry.retry_after(&self.config))
+            self.rejected.fetch_add(1, Ordering::Relaxed);
+            Err(status)
         }
     }
+
+    /// The caller's budget, without counting a request
+    pub fn status(&self, ip: &str) -> RateLimitStatus {
+        let config = self.config();
+        let now = Instant::now();
+        match self.trackers.get(ip) {
+            Some(tracker) => {
+                RateLimitStatus::new(self.name, &config, tracker.in_window(&config, now), tracker.reset_after(&config, now))
+            }
+            None => RateLimitStatus::new(self.name, &config, 0, Duration::ZERO),
+        }
+    }
+
+    /// The caller's budget with every named limiter that is still in use
+    pub fn statuses(ip: &str) -> Vec<RateLimitStatus> {
+        let registry = NAMED_LIMITERS.lock().unwrap_or_else(|e| e.into_inner());
+        registry.iter().filter_map(Weak::upgrade).map(|limiter| limiter.status(ip)).collect()
+    }
 }
 
 /// Axum middleware for rate limiting
@@ -184,9 +376,14 @@ pub async fn rate_limit_middleware(
 
     let ip = addr.ip().to_string();
 
-    match limiter.check(&ip) {
-        Ok(()) => Ok(next.run(request).await),
-        Err(retry_after) => {
+    match limiter.acquire(&ip) {
+        Ok(status) => {
+            let mut response = next.run(request).await;
+            status.apply_to(response.headers_mut());
+            Ok(response)
+        }
+        Err(status) => {
+            let retry_after = status.reset_seconds;
             // 🔒 SECURITY: Enhanced logging for rate limit violations
             // Helps detect brute force attacks and DDoS attempts
             tracing::warn!(
@@ -197,10 +394,13 @@ pub async fn rate_limit_middleware(
                 retry_after
             );
 
+            let mut headers = HeaderMap::new();
+            headers.insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+            status.apply_to(&mut headers);
             Err((
-                StatusCode::TOO_MANY_REQUESTS,
-                [("Retry-After", retry_after.to_string())],
-                format!("Rate limit exceeded. Try again in {} seconds.", retry_after),
+                headers,
+                super::problem_details::Problem::new(StatusCode::TOO_MANY_REQUESTS, "rate_limited")
+                    .with_detail(format!("Rate limit exceeded. Try again in {} seconds.", retry_after)),
             ))
         }
     }
@@ -245,6 +445,49 @@ mod tests {
         assert!(limiter.check("10.0.0.2").is_ok()); // Different IP, should work
     }
 
+    #[tokio::test]
+    async fn test_snapshot_reports_saturation() {
+        let limiter = RateLimiter::named("test", RateLimitConfig {
+            max_requests: 2,
+            window: Duration::from_secs(10),
+        });
+
+        assert!(limiter.check("10.1.0.1").is_ok());
+        assert!(limiter.check("10.1.0.1").is_ok());
+        assert!(limiter.check("10.1.0.1").is_err());
+        assert!(limiter.check("10.1.0.2").is_ok());
+
+        let snapshot = limiter.snapshot();
+        assert_eq!(snapshot.active_ips, 2);
+        assert_eq!(snapshot.saturated_ips, 1);
+        assert_eq!(snapshot.peak_usage_percent, 100.0);
+        assert_eq!(snapshot.rejected_total, 1);
+        assert!(RateLimiter::snapshots().iter().any(|s| s.name == "test"));
+    }
+
+    #[tokio::test]
+    async fn test_reconfigure_named_limiter() {
+        let limiter = RateLimiter::named("test_reconfigure", RateLimitConfig {
+            max_requests: 1,
+            window: Duration::from_secs(10),
+        });
+
+        assert!(limiter.check("10.2.0.1").is_ok());
+        assert!(limiter.check("10.2.0.1").is_err());
+
+        let changed = RateLimiter::reconfigure("test_reconfigure", RateLimitConfig {
+            max_requests: 3,
+            window: Duration::from_secs(10),
+        });
+        assert_eq!(changed, 1);
+        assert_eq!(limiter.config().max_requests, 3);
+
+        // Requests already counted stay counted against the new limit
+        assert!(limiter.check("10.2.0.1").is_ok());
+        assert!(limiter.check("10.2.0.1").is_ok());
+        assert!(limiter.check("10.2.0.1").is_err());
+    }
+
     #[tokio::test]
     async fn test_window_expiration() {
         let limiter = RateLimiter::new(RateLimitConfig {
@@ -259,4 +502,47 @@ mod tests {
 
         assert!(limiter.check("172.16.0.1").is_ok()); // Window expired, should work
     }
+
+    #[tokio::test]
+    async fn test_acquire_reports_budget() {
+        let limiter = RateLimiter::new(RateLimitConfig {
+            max_requests: 2,
+            window: Duration::from_secs(60),
+        });
+
+        assert_eq!(limiter.status("10.3.0.1").remaining, 2);
+        assert_eq!(limiter.status("10.3.0.1").reset_seconds, 0);
+
+        let first = limiter.acquire("10.3.0.1").unwrap();
+        assert_eq!((first.limit, first.remaining), (2, 1));
+        assert!(first.reset_seconds > 0 && first.reset_seconds <= 60);
+
+        assert_eq!(limiter.acquire("10.3.0.1").unwrap().remaining, 0);
+        let rejected = limiter.acquire("10.3.0.1").unwrap_err();
+        assert_eq!(rejected.remaining, 0);
+        assert!(rejected.reset_seconds > 0);
+
+        // Looking does not count
+        assert_eq!(limiter.status("10.3.0.1").remaining, 0);
+        assert_eq!(limiter.status("10.3.0.2").remaining, 2);
+    }
+
+    #[tokio::test]
+    async fn test_tightest_budget_wins_headers() {
+        let config = |max_requests| RateLimitConfig { max_requests, window: Duration::from_secs(60) };
+        let auth = RateLimiter::new(config(5)).acquire("10.4.0.1").unwrap();
+        let api = RateLimiter::new(config(100)).acquire("10.4.0.1").unwrap();
+
+        // The inner (auth) limiter sets its headers first, the outer one must not replace them
+        let mut headers = HeaderMap::new();
+        auth.apply_to(&mut headers);
+        api.apply_to(&mut headers);
+        assert_eq!(headers[RATE_LIMIT_LIMIT_HEADER], "5");
+        assert_eq!(headers[RATE_LIMIT_REMAINING_HEADER], "4");
+
+        let mut headers = HeaderMap::new();
+        api.apply_to(&mut headers);
+        auth.apply_to(&mut headers);
+        assert_eq!(headers[RATE_LIMIT_LIMIT_HEADER], "5");
+    }
 }
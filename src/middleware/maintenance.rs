@@ -0,0 +1,90 @@
+// ============================================================================
+// Maintenance Middleware - 503 for Non-Admin Traffic During Maintenance
+// ============================================================================
+//
+// While the `maintenance.enabled` runtime setting is on, every request gets
+// 503 Service Unavailable with Retry-After and the maintenance message, except:
+//
+// - requests with a valid admin or superadmin token (cookie or bearer), so
+//   admins can watch the drain and switch maintenance off again
+// - sign-in endpoints, so admins can get that token
+// - health checks and metrics, so probes and dashboards keep working
+//
+// The token is only checked for its role here; the routes' own auth middleware
+// still validates sessions as usual.
+//
+// ============================================================================
+
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use super::auth::peek_request_claims;
+use super::problem_details::Problem;
+use crate::{config::AppConfig, services::RuntimeSettings};
+
+/// Paths served during maintenance regardless of the caller (prefix match)
+const MAINTENANCE_EXEMPT_PATHS: &[&str] = &[
+    "/api/auth/login",
+    "/api/auth/refresh",
+    "/api/auth/logout",
+    "/api/auth/oauth/",
+    "/api/admin/health",
+    "/metrics",
+];
+
+pub fn is_maintenance_exempt(path: &str) -> bool {
+    MAINTENANCE_EXEMPT_PATHS.iter().any(|exempt| path.starts_with(exempt))
+}
+
+pub async fn maintenance_middleware(
+    State(config): State<AppConfig>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !RuntimeSettings::maintenance_enabled()
+        || is_maintenance_exempt(request.uri().path())
+        || is_admin_request(&config, &request)
+    {
+        return next.run(request).await;
+    }
+
+    maintenance_response()
+}
+
+fn is_admin_request(config: &AppConfig, request: &Request) -> bool {
+    peek_request_claims(config, request.headers())
+        .map(|claims| claims.is_admin())
+        .unwrap_or(false)
+}
+
+fn maintenance_response() -> Response {
+    let mut response = Problem::new(StatusCode::SERVICE_UNAVAILABLE, "maintenance")
+        .with_detail(RuntimeSettings::maintenance_message())
+        .into_response();
+    response.headers_mut().insert(
+        header::RETRY_AFTER,
+        HeaderValue::from(RuntimeSettings::maintenance_retry_after_seconds()),
+    );
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exempt_paths() {
+        assert!(is_maintenance_exempt("/api/auth/login"));
+        assert!(is_maintenance_exempt("/api/auth/oauth/google/callback"));
+        assert!(is_maintenance_exempt("/api/admin/health/ready"));
+        assert!(is_maintenance_exempt("/metrics"));
+
+        assert!(!is_maintenance_exempt("/api/auth/register"));
+        assert!(!is_maintenance_exempt("/api/inventory"));
+        assert!(!is_maintenance_exempt("/api/admin/users"));
+    }
+}
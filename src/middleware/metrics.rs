@@ -21,6 +21,47 @@
 //    - Counter: atlas_auth_failures_total
 //    - Labels: reason
 //
+// 5. **Catalog Syncs** (OpenFDA, EMA, regulator catalogs)
+//    - Histogram: atlas_catalog_sync_batch_duration_seconds (source)
+//    - Counter: atlas_catalog_sync_api_errors_total (source, status)
+//    - Gauge: atlas_catalog_sync_last_run_records (source, kind)
+//    - Gauge: atlas_catalog_sync_last_run_duration_seconds (source)
+//    - Gauge: atlas_catalog_sync_last_success_timestamp_seconds (source)
+//    - Gauge: atlas_catalog_sync_scheduler_lag_seconds (scheduler)
+//    - Histogram: atlas_catalog_upsert_rows_per_second (source)
+//    - Counter: atlas_catalog_upsert_rows_total (source)
+//
+// 6. **AI Response Cache**
+//    - Counter: atlas_ai_cache_lookups_total (feature, result)
+//    - Counter: atlas_ai_cache_saved_cost_usd_total (feature)
+//
+// 7. **AI Output Validation**
+//    - Counter: atlas_ai_output_validations_total (feature, result)
+//
+// 8. **Query Cache** (catalog lookups, marketplace searches)
+//    - Counter: atlas_query_cache_lookups_total (cache, result)
+//    - Counter: atlas_query_cache_invalidations_total (cache, reason)
+//    - Gauge: atlas_query_cache_entries (cache)
+//
+// 9. **Read Replica**
+//    - Gauge: atlas_db_replica_lag_seconds
+//    - Gauge: atlas_db_replica_reachable
+//    - Counter: atlas_db_read_routing_total (target, reason)
+//
+// 10. **Search Engine**
+//    - Counter: atlas_search_requests_total (index, backend)
+//    - Counter: atlas_search_engine_failures_total (index, operation)
+//    - Counter: atlas_search_index_documents_total (index, operation)
+//
+// 11. **Database Pool**
+//    - Gauge: atlas_db_pool_connections (pool, state)
+//    - Gauge: atlas_db_pool_saturation_ratio (pool)
+//    - Counter: atlas_db_pool_acquire_timeouts_total
+//    - Counter: atlas_query_budget_exceeded_total (path)
+//
+// 12. **Scheduler Leadership**
+//    - Gauge: atlas_scheduler_leader (scheduler)
+//
 // ## Endpoints:
 //
 // - GET /metrics - Prometheus scrape endpoint
@@ -56,10 +97,10 @@ use axum::{
 };
 use lazy_static::lazy_static;
 use prometheus::{
-    Encoder, TextEncoder, HistogramVec, CounterVec, GaugeVec, Opts, Registry,
-    register_histogram_vec, register_counter_vec, register_gauge_vec,
+    Encoder, TextEncoder, HistogramVec, Counter, CounterVec, Gauge, GaugeVec, Opts, Registry,
+    register_histogram_vec, register_counter, register_counter_vec, register_gauge, register_gauge_vec,
 };
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 // ============================================================================
 // PROMETHEUS METRICS REGISTRY
@@ -100,11 +141,34 @@ lazy_static! {
     ).unwrap();
 
     /// Database pool connections gauge
-    /// Tracks database connection pool state
+    /// Tracks database connection pool state (idle, in_use, max) of the primary and replica
     pub static ref DB_POOL_CONNECTIONS: GaugeVec = register_gauge_vec!(
         "atlas_db_pool_connections",
         "Database connection pool state",
-        &["state"]
+        &["pool", "state"]
+    ).unwrap();
+
+    /// Database pool saturation gauge
+    /// In-use share of the pool's max connections; requests queue for a connection at 1
+    pub static ref DB_POOL_SATURATION: GaugeVec = register_gauge_vec!(
+        "atlas_db_pool_saturation_ratio",
+        "In-use share of the database pool's maximum connections",
+        &["pool"]
+    ).unwrap();
+
+    /// Database pool acquire timeouts counter
+    /// Requests that gave up waiting for a connection (503)
+    pub static ref DB_POOL_ACQUIRE_TIMEOUTS_TOTAL: Counter = register_counter!(
+        "atlas_db_pool_acquire_timeouts_total",
+        "Total number of database connection acquire timeouts"
+    ).unwrap();
+
+    /// Query budget counter
+    /// Public endpoint requests cut off at PUBLIC_QUERY_BUDGET_MS
+    pub static ref QUERY_BUDGET_EXCEEDED_TOTAL: CounterVec = register_counter_vec!(
+        "atlas_query_budget_exceeded_total",
+        "Total number of requests that exceeded their query time budget",
+        &["path"]
     ).unwrap();
 
     /// API quota usage gauge
@@ -114,12 +178,199 @@ lazy_static! {
         "API quota usage percentage",
         &["user_id", "tier"]
     ).unwrap();
+
+    /// Catalog sync outcomes counter
+    /// Counts completed catalog syncs by source and status (completed or suspect)
+    pub static ref CATALOG_SYNC_RUNS_TOTAL: CounterVec = register_counter_vec!(
+        "atlas_catalog_sync_runs_total",
+        "Total number of completed catalog syncs",
+        &["source", "status"]
+    ).unwrap();
+
+    /// Catalog sync anomalies counter
+    /// Counts guardrail violations by source and anomaly kind
+    pub static ref CATALOG_SYNC_ANOMALIES_TOTAL: CounterVec = register_counter_vec!(
+        "atlas_catalog_sync_anomalies_total",
+        "Total number of catalog sync anomalies detected",
+        &["source", "kind"]
+    ).unwrap();
+
+    /// Suspect catalog sync gauge
+    /// 1 when the latest completed sync of a source was marked suspect
+    pub static ref CATALOG_SYNC_SUSPECT: GaugeVec = register_gauge_vec!(
+        "atlas_catalog_sync_suspect",
+        "Whether the latest catalog sync of a source is suspect",
+        &["source"]
+    ).unwrap();
+
+    /// Catalog sync batch duration histogram
+    /// Time to fetch and store one batch (page) of source records
+    pub static ref CATALOG_SYNC_BATCH_DURATION: HistogramVec = register_histogram_vec!(
+        "atlas_catalog_sync_batch_duration_seconds",
+        "Catalog sync batch duration in seconds (fetch and upsert)",
+        &["source"],
+        vec![0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0]
+    ).unwrap();
+
+    /// Catalog sync API errors counter
+    /// Counts failed source API requests by HTTP status ("network" or "parse" without one)
+    pub static ref CATALOG_SYNC_API_ERRORS_TOTAL: CounterVec = register_counter_vec!(
+        "atlas_catalog_sync_api_errors_total",
+        "Total number of failed catalog source API requests",
+        &["source", "status"]
+    ).unwrap();
+
+    /// Catalog sync record counts gauge
+    /// Records fetched, inserted, updated and failed by the latest finished sync
+    pub static ref CATALOG_SYNC_LAST_RUN_RECORDS: GaugeVec = register_gauge_vec!(
+        "atlas_catalog_sync_last_run_records",
+        "Records processed by the latest finished catalog sync",
+        &["source", "kind"]
+    ).unwrap();
+
+    /// Catalog sync duration gauge
+    pub static ref CATALOG_SYNC_LAST_RUN_DURATION: GaugeVec = register_gauge_vec!(
+        "atlas_catalog_sync_last_run_duration_seconds",
+        "Duration of the latest finished catalog sync in seconds",
+        &["source"]
+    ).unwrap();
+
+    /// Last successful catalog sync gauge (Unix time)
+    /// Alert on `time() - atlas_catalog_sync_last_success_timestamp_seconds` for stale catalogs
+    pub static ref CATALOG_SYNC_LAST_SUCCESS: GaugeVec = register_gauge_vec!(
+        "atlas_catalog_sync_last_success_timestamp_seconds",
+        "Unix time of the latest catalog sync that completed without anomalies",
+        &["source"]
+    ).unwrap();
+
+    /// Sync scheduler lag gauge
+    /// How late the latest scheduler tick ran compared to its schedule
+    pub static ref CATALOG_SYNC_SCHEDULER_LAG: GaugeVec = register_gauge_vec!(
+        "atlas_catalog_sync_scheduler_lag_seconds",
+        "Delay between a sync scheduler tick's deadline and its execution in seconds",
+        &["scheduler"]
+    ).unwrap();
+
+    /// Catalog upsert throughput histogram
+    /// Rows per second of each batch upsert statement (one chunk of entries)
+    pub static ref CATALOG_UPSERT_ROWS_PER_SECOND: HistogramVec = register_histogram_vec!(
+        "atlas_catalog_upsert_rows_per_second",
+        "Catalog batch upsert throughput in rows per second",
+        &["source"],
+        vec![500.0, 1000.0, 2500.0, 5000.0, 10000.0, 25000.0, 50000.0, 100000.0]
+    ).unwrap();
+
+    /// Catalog upserted rows counter
+    pub static ref CATALOG_UPSERT_ROWS_TOTAL: CounterVec = register_counter_vec!(
+        "atlas_catalog_upsert_rows_total",
+        "Total number of catalog entries written by batch upserts",
+        &["source"]
+    ).unwrap();
+
+    /// AI response cache lookups counter
+    /// Counts cache lookups by feature and result (hit or miss)
+    pub static ref AI_CACHE_LOOKUPS_TOTAL: CounterVec = register_counter_vec!(
+        "atlas_ai_cache_lookups_total",
+        "Total number of AI response cache lookups",
+        &["feature", "result"]
+    ).unwrap();
+
+    /// AI spend avoided by cache hits
+    pub static ref AI_CACHE_SAVED_COST_USD_TOTAL: CounterVec = register_counter_vec!(
+        "atlas_ai_cache_saved_cost_usd_total",
+        "Anthropic API cost avoided by AI response cache hits in USD",
+        &["feature"]
+    ).unwrap();
+
+    /// AI output validation counter
+    /// Counts structured responses by feature and result (valid, repaired or invalid)
+    pub static ref AI_OUTPUT_VALIDATIONS_TOTAL: CounterVec = register_counter_vec!(
+        "atlas_ai_output_validations_total",
+        "Total number of structured AI responses validated",
+        &["feature", "result"]
+    ).unwrap();
+
+    /// Query cache lookups counter
+    /// Counts lookups by cache and result (hit or miss); hit rate = hit / (hit + miss)
+    pub static ref QUERY_CACHE_LOOKUPS_TOTAL: CounterVec = register_counter_vec!(
+        "atlas_query_cache_lookups_total",
+        "Total number of query cache lookups",
+        &["cache", "result"]
+    ).unwrap();
+
+    /// Query cache invalidations counter
+    /// Counts cache flushes by reason (sync_completed, write, manual)
+    pub static ref QUERY_CACHE_INVALIDATIONS_TOTAL: CounterVec = register_counter_vec!(
+        "atlas_query_cache_invalidations_total",
+        "Total number of query cache invalidations",
+        &["cache", "reason"]
+    ).unwrap();
+
+    /// Query cache size gauge
+    pub static ref QUERY_CACHE_ENTRIES: GaugeVec = register_gauge_vec!(
+        "atlas_query_cache_entries",
+        "Number of entries held by the query cache",
+        &["cache"]
+    ).unwrap();
+
+    /// Read replica lag gauge
+    /// Latest lag measured by the replica lag monitor
+    pub static ref DB_REPLICA_LAG: Gauge = register_gauge!(
+        "atlas_db_replica_lag_seconds",
+        "Replication lag of the read replica in seconds"
+    ).unwrap();
+
+    /// Read replica reachability gauge
+    pub static ref DB_REPLICA_REACHABLE: Gauge = register_gauge!(
+        "atlas_db_replica_reachable",
+        "Whether the latest read replica lag check succeeded"
+    ).unwrap();
+
+    /// Read routing counter
+    /// Counts routed reads by target (replica or primary) and the reason for it
+    pub static ref DB_READ_ROUTING_TOTAL: CounterVec = register_counter_vec!(
+        "atlas_db_read_routing_total",
+        "Total number of routed database reads",
+        &["target", "reason"]
+    ).unwrap();
+
+    /// Search request counter
+    /// Counts catalog and marketplace searches by the backend that answered
+    /// (engine or postgres)
+    pub static ref SEARCH_REQUESTS_TOTAL: CounterVec = register_counter_vec!(
+        "atlas_search_requests_total",
+        "Total number of searches by answering backend",
+        &["index", "backend"]
+    ).unwrap();
+
+    /// Search engine failure counter
+    /// Failed searches fall back to Postgres; failed index batches are retried
+    pub static ref SEARCH_ENGINE_FAILURES_TOTAL: CounterVec = register_counter_vec!(
+        "atlas_search_engine_failures_total",
+        "Total number of failed search engine calls",
+        &["index", "operation"]
+    ).unwrap();
+
+    /// Indexed document counter
+    pub static ref SEARCH_INDEX_DOCUMENTS_TOTAL: CounterVec = register_counter_vec!(
+        "atlas_search_index_documents_total",
+        "Total number of documents upserted into or deleted from the search engine",
+        &["index", "operation"]
+    ).unwrap();
+
+    /// Scheduler leadership gauge
+    /// 1 for the schedulers this instance leads, 0 for the ones another instance leads
+    pub static ref SCHEDULER_LEADER: GaugeVec = register_gauge_vec!(
+        "atlas_scheduler_leader",
+        "Whether this instance leads the scheduler",
+        &["scheduler"]
+    ).unwrap();
 }
 
 /// Simplify path for metrics (remove IDs)
 ///
 /// Example: /api/users/123 -> /api/users/:id
-fn normalize_path(path: &str) -> String {
+pub(crate) fn normalize_path(path: &str) -> String {
     let segments: Vec<&str> = path.split('/').collect();
     let mut normalized = Vec::new();
 
@@ -204,6 +455,14 @@ pub async fn metrics_middleware(
 ///
 /// Returns Prometheus-formatted metrics for scraping
 ///
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "metrics",
+    responses(
+        (status = 200, description = "Prometheus text exposition format", body = String, content_type = "text/plain; version=0.0.4"),
+    )
+)]
 pub async fn metrics_handler() -> impl IntoResponse {
     let encoder = TextEncoder::new();
     let metric_families = prometheus::gather();
@@ -246,11 +505,26 @@ pub fn record_auth_failure(reason: &str) {
 
 /// Record database pool state
 ///
-/// Call this periodically to track connection pool health
+/// Called periodically by the pool monitor (`config::pool::run_pool_monitor`)
 ///
-pub fn record_db_pool_state(idle: usize, active: usize) {
-    DB_POOL_CONNECTIONS.with_label_values(&["idle"]).set(idle as f64);
-    DB_POOL_CONNECTIONS.with_label_values(&["active"]).set(active as f64);
+pub fn record_db_pool_state(pool: &str, max_connections: u32, size: u32, idle: usize) {
+    let in_use = size.saturating_sub(idle as u32);
+    DB_POOL_CONNECTIONS.with_label_values(&[pool, "idle"]).set(idle as f64);
+    DB_POOL_CONNECTIONS.with_label_values(&[pool, "in_use"]).set(in_use as f64);
+    DB_POOL_CONNECTIONS.with_label_values(&[pool, "max"]).set(max_connections as f64);
+    if max_connections > 0 {
+        DB_POOL_SATURATION.with_label_values(&[pool]).set(in_use as f64 / max_connections as f64);
+    }
+}
+
+/// Record a request that timed out waiting for a database connection
+pub fn record_db_pool_acquire_timeout() {
+    DB_POOL_ACQUIRE_TIMEOUTS_TOTAL.inc();
+}
+
+/// Record a request cut off at its query time budget
+pub fn record_query_budget_exceeded(path: &str) {
+    QUERY_BUDGET_EXCEEDED_TOTAL.with_label_values(&[path]).inc();
 }
 
 /// Record API quota usage
@@ -263,6 +537,147 @@ pub fn record_api_quota_usage(user_id: &str, tier: &str, usage_percent: f64) {
         .set(usage_percent);
 }
 
+/// Record the outcome of a completed catalog sync
+///
+/// Call this after the anomaly guardrails have run; `anomalies` are the kinds detected
+///
+pub fn record_catalog_sync(source: &str, anomalies: &[&str]) {
+    let status = if anomalies.is_empty() { "completed" } else { "suspect" };
+    CATALOG_SYNC_RUNS_TOTAL.with_label_values(&[source, status]).inc();
+    CATALOG_SYNC_SUSPECT
+        .with_label_values(&[source])
+        .set(if anomalies.is_empty() { 0.0 } else { 1.0 });
+
+    for kind in anomalies {
+        CATALOG_SYNC_ANOMALIES_TOTAL.with_label_values(&[source, kind]).inc();
+    }
+    if anomalies.is_empty() {
+        CATALOG_SYNC_LAST_SUCCESS
+            .with_label_values(&[source])
+            .set(chrono::Utc::now().timestamp() as f64);
+    }
+}
+
+/// Record the duration of one catalog sync batch
+pub fn record_sync_batch(source: &str, duration: Duration) {
+    CATALOG_SYNC_BATCH_DURATION
+        .with_label_values(&[source])
+        .observe(duration.as_secs_f64());
+}
+
+/// Record the size and duration of one catalog batch upsert statement
+pub fn record_catalog_upsert(source: &str, rows: usize, duration: Duration) {
+    CATALOG_UPSERT_ROWS_TOTAL
+        .with_label_values(&[source])
+        .inc_by(rows as f64);
+    if rows > 0 {
+        CATALOG_UPSERT_ROWS_PER_SECOND
+            .with_label_values(&[source])
+            .observe(rows as f64 / duration.as_secs_f64().max(f64::EPSILON));
+    }
+}
+
+/// Record a failed source API request
+///
+/// `status` is the HTTP status code, or "network" / "parse" for requests without one
+///
+pub fn record_sync_api_error(source: &str, status: &str) {
+    CATALOG_SYNC_API_ERRORS_TOTAL.with_label_values(&[source, status]).inc();
+}
+
+/// Record the record counts and duration of a finished catalog sync
+pub fn record_sync_run(source: &str, fetched: i32, inserted: i32, updated: i32, failed: i32, duration: Duration) {
+    for (kind, count) in [("fetched", fetched), ("inserted", inserted), ("updated", updated), ("failed", failed)] {
+        CATALOG_SYNC_LAST_RUN_RECORDS
+            .with_label_values(&[source, kind])
+            .set(count as f64);
+    }
+    CATALOG_SYNC_LAST_RUN_DURATION
+        .with_label_values(&[source])
+        .set(duration.as_secs_f64());
+}
+
+/// Record how late a scheduler tick ran
+///
+/// Pass the deadline returned by `tokio::time::Interval::tick`
+///
+pub fn record_scheduler_lag(scheduler: &str, deadline: tokio::time::Instant) {
+    CATALOG_SYNC_SCHEDULER_LAG
+        .with_label_values(&[scheduler])
+        .set(deadline.elapsed().as_secs_f64());
+}
+
+/// Record an AI response cache lookup
+///
+/// `saved_cost_usd` is the original call's cost on a hit, `None` on a miss
+///
+pub fn record_ai_cache_lookup(feature: &str, saved_cost_usd: Option<f64>) {
+    let result = if saved_cost_usd.is_some() { "hit" } else { "miss" };
+    AI_CACHE_LOOKUPS_TOTAL.with_label_values(&[feature, result]).inc();
+
+    if let Some(cost) = saved_cost_usd {
+        AI_CACHE_SAVED_COST_USD_TOTAL.with_label_values(&[feature]).inc_by(cost.max(0.0));
+    }
+}
+
+/// Record the outcome of validating a structured AI response
+///
+/// `result` is "valid", "repaired" (valid after the repair retry) or "invalid"
+///
+pub fn record_ai_output_validation(feature: &str, result: &str) {
+    AI_OUTPUT_VALIDATIONS_TOTAL.with_label_values(&[feature, result]).inc();
+}
+
+/// Record a query cache lookup
+pub fn record_query_cache_lookup(cache: &str, hit: bool) {
+    let result = if hit { "hit" } else { "miss" };
+    QUERY_CACHE_LOOKUPS_TOTAL.with_label_values(&[cache, result]).inc();
+}
+
+/// Record a query cache invalidation and the cache's size afterwards
+pub fn record_query_cache_invalidation(cache: &str, reason: &str, entries: usize) {
+    QUERY_CACHE_INVALIDATIONS_TOTAL.with_label_values(&[cache, reason]).inc();
+    QUERY_CACHE_ENTRIES.with_label_values(&[cache]).set(entries as f64);
+}
+
+/// Record the number of entries a query cache holds
+pub fn record_query_cache_entries(cache: &str, entries: usize) {
+    QUERY_CACHE_ENTRIES.with_label_values(&[cache]).set(entries as f64);
+}
+
+/// Record a read replica lag check; None when the replica could not be reached
+pub fn record_replica_lag(lag: Option<Duration>) {
+    DB_REPLICA_REACHABLE.set(if lag.is_some() { 1.0 } else { 0.0 });
+    if let Some(lag) = lag {
+        DB_REPLICA_LAG.set(lag.as_secs_f64());
+    }
+}
+
+/// Record where a routed read went
+pub fn record_read_routing(target: &str, reason: &str) {
+    DB_READ_ROUTING_TOTAL.with_label_values(&[target, reason]).inc();
+}
+
+/// Record which backend answered a search: "engine" or "postgres"
+pub fn record_search_request(index: &str, backend: &str) {
+    SEARCH_REQUESTS_TOTAL.with_label_values(&[index, backend]).inc();
+}
+
+/// Record a failed search engine call; `operation` is "search" or "index"
+pub fn record_search_engine_failure(index: &str, operation: &str) {
+    SEARCH_ENGINE_FAILURES_TOTAL.with_label_values(&[index, operation]).inc();
+}
+
+/// Record documents sent to the search engine; `operation` is "upsert" or "delete"
+pub fn record_search_indexed(index: &str, operation: &str, documents: usize) {
+    SEARCH_INDEX_DOCUMENTS_TOTAL.with_label_values(&[index, operation]).inc_by(documents as f64);
+}
+
+/// Record whether this instance leads a scheduler
+pub fn record_scheduler_leader(scheduler: &str, leads: bool) {
+    SCHEDULER_LEADER.with_label_values(&[scheduler]).set(if leads { 1.0 } else { 0.0 });
+}
+
 // ============================================================================
 // TESTS
 // ============================================================================
@@ -287,6 +702,16 @@ mod tests {
         assert_eq!(normalize_path("/api/auth/login"), "/api/auth/login");
     }
 
+    #[test]
+    fn test_record_sync_run() {
+        record_sync_run("test_source", 10, 4, 5, 1, Duration::from_secs(3));
+        assert_eq!(
+            CATALOG_SYNC_LAST_RUN_RECORDS.with_label_values(&["test_source", "inserted"]).get(),
+            4.0
+        );
+        assert_eq!(CATALOG_SYNC_LAST_RUN_DURATION.with_label_values(&["test_source"]).get(), 3.0);
+    }
+
     #[test]
     fn test_record_auth_failure() {
         record_auth_failure("invalid_password");
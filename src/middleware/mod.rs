@@ -8,6 +8,15 @@ pub mod csrf_protection;
 pub mod request_id;
 pub mod content_type_validation;
 pub mod metrics;
+pub mod request_logging;
+pub mod error_tracking;
+pub mod maintenance;
+pub mod route_policy;
+pub mod payload_capture;
+pub mod replica_routing;
+pub mod query_budget;
+pub mod problem_details;
+pub mod field_selection;
 
 pub use admin::*;
 pub use auth::*;
@@ -18,4 +27,13 @@ pub use security_headers::*;
 pub use csrf_protection::*;
 pub use request_id::*;
 pub use content_type_validation::*;
-pub use metrics::*;
\ No newline at end of file
+pub use metrics::*;
+pub use request_logging::*;
+pub use error_tracking::*;
+pub use maintenance::*;
+pub use route_policy::*;
+pub use payload_capture::*;
+pub use replica_routing::*;
+pub use query_budget::*;
+pub use problem_details::*;
+pub use field_selection::*;
\ No newline at end of file
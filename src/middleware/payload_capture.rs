@@ -0,0 +1,319 @@
+// ============================================================================
+// Payload Capture Middleware - Request/Response Bodies for Investigations
+// ============================================================================
+//
+// 🔒 SECURITY: Only requests matching an active capture rule (opened by a
+// superadmin for one user and/or route, see PayloadCaptureService) are captured;
+// everything else passes straight through without touching the database.
+//
+// ## What is stored:
+//
+// - method, route template, path, query string, status, latency, request id
+// - request and response bodies, redacted: JSON and form fields named like secrets
+//   (password, token, api_key, ...) are replaced, and every string passes through
+//   `log_sanitizer::redact_pii` (emails, JWTs, bearer tokens, phone numbers)
+// - bodies are cut at 64 KiB; binary bodies are recorded by size only
+//
+// Headers are never stored (cookies, authorization). Sign-in endpoints (including
+// the FHIR client-credentials exchange) and the capture API itself are never
+// captured, whatever the rules say. Streaming
+// responses (server-sent events) and bodies over 1 MiB are not buffered.
+//
+// ============================================================================
+
+use axum::{
+    body::{to_bytes, Body, Bytes, HttpBody},
+    extract::{MatchedPath, Request, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde_json::Value;
+use std::time::Instant;
+
+use super::{auth::peek_request_claims, request_id::get_request_id};
+use crate::{
+    config::AppConfig,
+    models::payload_capture::NewPayloadCapture,
+    services::PayloadCaptureService,
+    utils::log_sanitizer::redact_pii,
+};
+
+/// Largest body buffered for capture; larger ones pass through uncaptured
+const MAX_BUFFERED_BODY_BYTES: usize = 1024 * 1024;
+
+/// Stored bodies are cut at this size
+const MAX_CAPTURED_BODY_BYTES: usize = 64 * 1024;
+
+/// Paths never captured (prefix match)
+const NEVER_CAPTURED_PATHS: &[&str] = &[
+    "/api/auth/",
+    "/fhir/auth/",
+    "/api/admin/payload-captures",
+    "/api/admin/health",
+    "/metrics",
+];
+
+/// JSON and form fields whose values are dropped, matched case-insensitively as substrings
+const SECRET_FIELDS: &[&str] = &[
+    "password",
+    "secret",
+    "token",
+    "api_key",
+    "apikey",
+    "authorization",
+    "private_key",
+    "otp",
+    "mfa_code",
+    "card_number",
+    "cvv",
+];
+
+pub fn is_never_captured(path: &str) -> bool {
+    NEVER_CAPTURED_PATHS.iter().any(|never| path.starts_with(never))
+}
+
+pub async fn payload_capture_middleware(
+    State(config): State<AppConfig>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !PayloadCaptureService::has_active_rules() || is_never_captured(request.uri().path()) {
+        return next.run(request).await;
+    }
+
+    let user_id = peek_request_claims(&config, request.headers()).map(|claims| claims.user_id);
+    let method = request.method().to_string();
+    let path = request.uri().path().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|matched| matched.as_str().to_string())
+        .unwrap_or_else(|| path.clone());
+
+    let Some(rule) = PayloadCaptureService::matching_rule(user_id, &method, &route, &path) else {
+        return next.run(request).await;
+    };
+
+    let request_id = get_request_id(request.extensions());
+    let query = request.uri().query().map(|query| redact_pii(query).into_owned());
+    let request_content_type = content_type(request.headers());
+
+    let (parts, body) = request.into_parts();
+    let (body, request_bytes) = match buffer_body(body, &parts.headers).await {
+        Ok(buffered) => buffered,
+        Err(e) => {
+            tracing::warn!("Failed to read request body for capture: {}", e);
+            return StatusCode::BAD_REQUEST.into_response();
+        }
+    };
+
+    let started = Instant::now();
+    let response = next.run(Request::from_parts(parts, body)).await;
+    let latency_ms = started.elapsed().as_millis().min(i32::MAX as u128) as i32;
+
+    let status = response.status().as_u16();
+    let response_content_type = content_type(response.headers());
+    let (parts, body) = response.into_parts();
+    let (body, response_bytes) = match buffer_body(body, &parts.headers).await {
+        Ok(buffered) => buffered,
+        Err(e) => {
+            tracing::error!("Failed to read response body for capture: {}", e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let (request_body, request_truncated) = redact_body(request_content_type.as_deref(), request_bytes.as_deref());
+    let (response_body, response_truncated) = redact_body(response_content_type.as_deref(), response_bytes.as_deref());
+
+    let capture = NewPayloadCapture {
+        rule_id: rule.id,
+        retention_days: rule.retention_days,
+        request_id,
+        user_id,
+        method,
+        route,
+        path,
+        query,
+        status,
+        latency_ms,
+        request_content_type,
+        request_body,
+        response_content_type,
+        response_body,
+        truncated: request_truncated || response_truncated,
+    };
+    let service = PayloadCaptureService::new(config.database_pool.clone());
+    tokio::spawn(async move {
+        if let Err(e) = service.record(&capture).await {
+            tracing::error!("Failed to store payload capture for rule {}: {:?}", capture.rule_id, e);
+        }
+    });
+
+    Response::from_parts(parts, body)
+}
+
+fn content_type(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+}
+
+/// Buffer a body small enough to capture and hand back an equivalent one; streams
+/// and large bodies are returned untouched with nothing captured
+async fn buffer_body(body: Body, headers: &HeaderMap) -> Result<(Body, Option<Bytes>), axum::Error> {
+    let streaming = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|content_type| content_type.starts_with("text/event-stream"));
+    let small = body
+        .size_hint()
+        .upper()
+        .is_some_and(|upper| upper <= MAX_BUFFERED_BODY_BYTES as u64);
+
+    if streaming || !small {
+        return Ok((body, None));
+    }
+
+    let bytes = to_bytes(body, MAX_BUFFERED_BODY_BYTES).await?;
+    Ok((Body::from(bytes.clone()), Some(bytes)))
+}
+
+/// Redacted, size-limited text of a body, and whether it was cut
+pub fn redact_body(content_type: Option<&str>, bytes: Option<&[u8]>) -> (Option<String>, bool) {
+    let bytes = match bytes {
+        Some(bytes) if !bytes.is_empty() => bytes,
+        _ => return (None, false),
+    };
+    let content_type = content_type.unwrap_or("").to_ascii_lowercase();
+
+    let text = if content_type.contains("json") {
+        match serde_json::from_slice::<Value>(bytes) {
+            Ok(mut value) => {
+                redact_json(&mut value);
+                value.to_string()
+            }
+            Err(_) => redact_pii(&String::from_utf8_lossy(bytes)).into_owned(),
+        }
+    } else if content_type.starts_with("application/x-www-form-urlencoded") {
+        redact_form(bytes)
+    } else if content_type.starts_with("text/") || content_type.contains("xml") {
+        redact_pii(&String::from_utf8_lossy(bytes)).into_owned()
+    } else {
+        return (Some(format!("[binary body, {} bytes]", bytes.len())), false);
+    };
+
+    if text.len() <= MAX_CAPTURED_BODY_BYTES {
+        return (Some(text), false);
+    }
+    let mut end = MAX_CAPTURED_BODY_BYTES;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    (Some(text[..end].to_string()), true)
+}
+
+fn is_secret_field(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    SECRET_FIELDS.iter().any(|secret| name.contains(secret))
+}
+
+fn redact_form(bytes: &[u8]) -> String {
+    let mut redacted = url::form_urlencoded::Serializer::new(String::new());
+    for (name, value) in url::form_urlencoded::parse(bytes) {
+        if is_secret_field(&name) {
+            redacted.append_pair(&name, "[REDACTED]");
+        } else {
+            redacted.append_pair(&name, &redact_pii(&value));
+        }
+    }
+    redacted.finish()
+}
+
+fn redact_json(value: &mut Value) {
+    match value {
+        Value::Object(fields) => {
+            for (key, field) in fields.iter_mut() {
+                if is_secret_field(key) {
+                    *field = Value::String("[REDACTED]".to_string());
+                } else {
+                    redact_json(field);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_json),
+        Value::String(text) => {
+            if let std::borrow::Cow::Owned(redacted) = redact_pii(text) {
+                *text = redacted;
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_bodies_are_redacted() {
+        let body = br#"{"email":"jane@pharmacy.com","new_password":"hunter2","items":[{"ndc":"0002-1433","note":"call +1 555 010 9999"}],"refresh_token":"abc"}"#;
+        let (text, truncated) = redact_body(Some("application/json"), Some(body));
+        let value: Value = serde_json::from_str(&text.unwrap()).unwrap();
+
+        assert!(!truncated);
+        assert_eq!(value["email"], "[EMAIL]");
+        assert_eq!(value["new_password"], "[REDACTED]");
+        assert_eq!(value["refresh_token"], "[REDACTED]");
+        assert_eq!(value["items"][0]["ndc"], "0002-1433");
+        assert_eq!(value["items"][0]["note"], "call [PHONE]");
+    }
+
+    #[test]
+    fn test_form_bodies_are_redacted() {
+        let body = b"grant_type=client_credentials&client_id=atlas-h1&client_secret=s3cr%26t&contact=jane%40pharmacy.com";
+        let (text, truncated) = redact_body(Some("application/x-www-form-urlencoded"), Some(body));
+        let text = text.unwrap();
+        let fields: Vec<(String, String)> = url::form_urlencoded::parse(text.as_bytes()).into_owned().collect();
+
+        assert!(!truncated);
+        assert!(!text.contains("s3cr"));
+        assert_eq!(
+            fields,
+            vec![
+                ("grant_type".to_string(), "client_credentials".to_string()),
+                ("client_id".to_string(), "atlas-h1".to_string()),
+                ("client_secret".to_string(), "[REDACTED]".to_string()),
+                ("contact".to_string(), "[EMAIL]".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_body_kinds_and_truncation() {
+        assert_eq!(redact_body(Some("application/json"), None), (None, false));
+        assert_eq!(redact_body(Some("application/json"), Some(b"")), (None, false));
+        assert_eq!(
+            redact_body(Some("application/pdf"), Some(&[0x25, 0x50, 0x44, 0x46])),
+            (Some("[binary body, 4 bytes]".to_string()), false)
+        );
+        assert_eq!(
+            redact_body(Some("text/plain"), Some(b"from ops@atlas.com")),
+            (Some("from [EMAIL]".to_string()), false)
+        );
+
+        let large = "é".repeat(MAX_CAPTURED_BODY_BYTES);
+        let (text, truncated) = redact_body(Some("text/csv"), Some(large.as_bytes()));
+        assert!(truncated);
+        assert!(text.unwrap().len() <= MAX_CAPTURED_BODY_BYTES);
+    }
+
+    #[test]
+    fn test_never_captured_paths() {
+        assert!(is_never_captured("/api/auth/login"));
+        assert!(is_never_captured("/fhir/auth/token"));
+        assert!(is_never_captured("/api/admin/payload-captures/rules"));
+        assert!(!is_never_captured("/api/inquiries"));
+    }
+}
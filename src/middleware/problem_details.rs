@@ -0,0 +1,610 @@
+// ============================================================================
+// Problem Details - RFC 7807 Error Envelope for Every Error Response
+// ============================================================================
+//
+// Every 4xx/5xx response of the API is an `application/problem+json` document:
+//
+// ```json
+// {
+//   "type": "urn:atlas-pharma:problem:validation_failed",
+//   "title": "Validation failed",
+//   "status": 400,
+//   "code": "validation_failed",
+//   "request_id": "6f1c…",
+//   "errors": [{ "field": "quantity", "code": "range", "message": "must be at least 1" }],
+//   "error": "Validation failed"
+// }
+// ```
+//
+// - `code` is stable and machine-readable; `type` is the same code as a URN
+// - `title` and field messages follow Accept-Language (en, de, es, fr; English
+//   otherwise) and the response carries Content-Language
+// - `detail` is the error's own message (NotFound, BadRequest, ...), in English;
+//   generic errors have no detail and keep internal details out as before
+// - `request_id` matches the X-Request-ID header, for support requests
+// - `error` repeats detail (or title) for clients of the old `{error, status}` body
+//
+// Errors are built as `Problem`s (`AppError` converts itself) and rendered in
+// English; `problem_details_middleware` renders them again with the caller's
+// locale and request id. Error responses without a problem (bare status codes
+// from middleware, axum rejections and unknown routes) are converted from their
+// status, with a plain-text body as the detail. JSON error bodies of other
+// formats (FHIR OperationOutcome, OAuth token errors) are left alone.
+//
+// ============================================================================
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::Request,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use utoipa::ToSchema;
+use uuid::Uuid;
+use validator::{ValidationErrors, ValidationErrorsKind};
+
+use super::request_id::get_request_id;
+
+/// Content type of problem documents
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+/// `type` of a problem is this prefix and its code
+pub const PROBLEM_TYPE_PREFIX: &str = "urn:atlas-pharma:problem:";
+
+/// Largest plain-text error body used as a detail
+const MAX_TEXT_DETAIL_BYTES: usize = 4 * 1024;
+
+/// Languages of titles and field messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Locale {
+    #[default]
+    En,
+    De,
+    Es,
+    Fr,
+}
+
+impl Locale {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::De => "de",
+            Locale::Es => "es",
+            Locale::Fr => "fr",
+        }
+    }
+
+    fn from_tag(tag: &str) -> Option<Self> {
+        let primary = tag.split(['-', '_']).next()?.trim().to_lowercase();
+        match primary.as_str() {
+            "en" => Some(Locale::En),
+            "de" => Some(Locale::De),
+            "es" => Some(Locale::Es),
+            "fr" => Some(Locale::Fr),
+            _ => None,
+        }
+    }
+
+    /// The supported language with the highest q-value, English when none is
+    pub fn from_accept_language(header: &str) -> Self {
+        let mut best: Option<(Locale, f32)> = None;
+        for range in header.split(',') {
+            let mut parts = range.split(';');
+            let Some(locale) = parts.next().and_then(Locale::from_tag) else {
+                continue;
+            };
+            let quality = parts
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .and_then(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            if quality <= 0.0 {
+                continue;
+            }
+            if best.map_or(true, |(_, best_quality)| quality > best_quality) {
+                best = Some((locale, quality));
+            }
+        }
+        best.map(|(locale, _)| locale).unwrap_or_default()
+    }
+
+    fn from_headers(headers: &HeaderMap) -> Self {
+        headers
+            .get(header::ACCEPT_LANGUAGE)
+            .and_then(|value| value.to_str().ok())
+            .map(Locale::from_accept_language)
+            .unwrap_or_default()
+    }
+}
+
+/// One failed validation of a request field
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct FieldError {
+    /// Path of the field, e.g. `items[2].quantity`
+    pub field: String,
+    /// Validator code: length, range, email, url, required, or a custom one
+    pub code: String,
+    /// Localized message
+    pub message: String,
+    /// The validator's own message (English)
+    #[serde(skip)]
+    custom_message: Option<String>,
+    /// Bounds of length and range checks
+    #[serde(skip)]
+    min: Option<Value>,
+    #[serde(skip)]
+    max: Option<Value>,
+}
+
+impl FieldError {
+    fn localized(&self, locale: Locale) -> String {
+        if locale == Locale::En {
+            if let Some(message) = &self.custom_message {
+                return message.clone();
+            }
+        }
+        field_message(&self.code, locale, self.min.as_ref(), self.max.as_ref())
+    }
+}
+
+/// Flatten validator errors to field errors; nested fields are dotted paths
+pub fn field_errors(errors: &ValidationErrors) -> Vec<FieldError> {
+    let mut out = Vec::new();
+    collect_field_errors(errors, "", &mut out);
+    out.sort_by(|a, b| a.field.cmp(&b.field));
+    out
+}
+
+fn collect_field_errors(errors: &ValidationErrors, prefix: &str, out: &mut Vec<FieldError>) {
+    for (field, kind) in errors.errors() {
+        let path = if prefix.is_empty() { field.to_string() } else { format!("{}.{}", prefix, field) };
+        match kind {
+            ValidationErrorsKind::Field(field_errors) => {
+                for error in field_errors {
+                    // `value` is never copied: it may be a password or PII
+                    let mut error = FieldError {
+                        field: path.clone(),
+                        code: error.code.to_string(),
+                        message: String::new(),
+                        custom_message: error.message.as_ref().map(|message| message.to_string()),
+                        min: error.params.get("min").or_else(|| error.params.get("equal")).cloned(),
+                        max: error.params.get("max").or_else(|| error.params.get("equal")).cloned(),
+                    };
+                    error.message = error.localized(Locale::En);
+                    out.push(error);
+                }
+            }
+            ValidationErrorsKind::Struct(nested) => collect_field_errors(nested, &path, out),
+            ValidationErrorsKind::List(items) => {
+                for (index, nested) in items {
+                    collect_field_errors(nested, &format!("{}[{}]", path, index), out);
+                }
+            }
+        }
+    }
+}
+
+/// An error response (RFC 7807)
+#[derive(Debug, Clone)]
+pub struct Problem {
+    pub status: StatusCode,
+    pub code: &'static str,
+    pub detail: Option<String>,
+    pub errors: Vec<FieldError>,
+    /// Additional members of the document
+    pub extensions: Map<String, Value>,
+}
+
+impl Problem {
+    pub fn new(status: StatusCode, code: &'static str) -> Self {
+        Self {
+            status,
+            code,
+            detail: None,
+            errors: Vec::new(),
+            extensions: Map::new(),
+        }
+    }
+
+    /// A problem with the generic code of its status
+    pub fn from_status(status: StatusCode) -> Self {
+        Self::new(status, code_for_status(status))
+    }
+
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    pub fn with_field_errors(mut self, errors: &ValidationErrors) -> Self {
+        self.errors = field_errors(errors);
+        self
+    }
+
+    pub fn with_extension(mut self, name: &str, value: Value) -> Self {
+        self.extensions.insert(name.to_string(), value);
+        self
+    }
+
+    /// The problem document in `locale`
+    pub fn to_json(&self, locale: Locale, request_id: Option<Uuid>) -> Value {
+        let title = title(self.code, locale);
+        let mut body = Map::new();
+        body.insert("type".to_string(), json!(format!("{}{}", PROBLEM_TYPE_PREFIX, self.code)));
+        body.insert("title".to_string(), json!(title));
+        body.insert("status".to_string(), json!(self.status.as_u16()));
+        if let Some(detail) = &self.detail {
+            body.insert("detail".to_string(), json!(detail));
+        }
+        body.insert("code".to_string(), json!(self.code));
+        if let Some(request_id) = request_id {
+            body.insert("request_id".to_string(), json!(request_id.to_string()));
+        }
+        if !self.errors.is_empty() {
+            let errors: Vec<FieldError> = self
+                .errors
+                .iter()
+                .map(|error| FieldError { message: error.localized(locale), ..error.clone() })
+                .collect();
+            body.insert("errors".to_string(), json!(errors));
+        }
+        body.insert("error".to_string(), json!(self.detail.as_deref().unwrap_or(title)));
+        for (name, value) in &self.extensions {
+            body.entry(name.clone()).or_insert_with(|| value.clone());
+        }
+        Value::Object(body)
+    }
+
+    fn render(&self, locale: Locale, request_id: Option<Uuid>, headers: &mut HeaderMap) -> Body {
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON));
+        headers.insert(header::CONTENT_LANGUAGE, HeaderValue::from_static(locale.as_str()));
+        headers.remove(header::CONTENT_LENGTH);
+        Body::from(self.to_json(locale, request_id).to_string())
+    }
+}
+
+impl IntoResponse for Problem {
+    /// English and without request id; `problem_details_middleware` renders the
+    /// problem again for the request
+    fn into_response(self) -> Response {
+        let mut headers = HeaderMap::new();
+        let body = self.render(Locale::En, None, &mut headers);
+        let mut response = (self.status, headers, body).into_response();
+        response.extensions_mut().insert(self);
+        response
+    }
+}
+
+/// Render every error response as a problem document in the caller's language
+/// with the request id
+///
+/// Must run inside `request_id_middleware` and outside every middleware that
+/// can reject a request.
+pub async fn problem_details_middleware(request: Request, next: Next) -> Response {
+    let locale = Locale::from_headers(request.headers());
+    let request_id = get_request_id(request.extensions());
+
+    let response = next.run(request).await;
+    if !response.status().is_client_error() && !response.status().is_server_error() {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let problem = match parts.extensions.remove::<Problem>() {
+        Some(problem) => problem,
+        None if is_plain_text(&parts.headers) => {
+            let text = to_bytes(body, MAX_TEXT_DETAIL_BYTES)
+                .await
+                .map(|bytes| String::from_utf8_lossy(&bytes).trim().to_string())
+                .unwrap_or_default();
+            let problem = Problem::from_status(parts.status);
+            if text.is_empty() { problem } else { problem.with_detail(text) }
+        }
+        None => return Response::from_parts(parts, body),
+    };
+
+    let body = problem.render(locale, request_id, &mut parts.headers);
+    Response::from_parts(parts, body)
+}
+
+/// Bodies of bare status codes and axum rejections
+fn is_plain_text(headers: &HeaderMap) -> bool {
+    match headers.get(header::CONTENT_TYPE).and_then(|value| value.to_str().ok()) {
+        None => true,
+        Some(content_type) => content_type.trim().to_lowercase().starts_with("text/plain"),
+    }
+}
+
+/// Code of an error response that has no more specific one
+pub fn code_for_status(status: StatusCode) -> &'static str {
+    match status {
+        StatusCode::BAD_REQUEST => "bad_request",
+        StatusCode::UNAUTHORIZED => "unauthorized",
+        StatusCode::FORBIDDEN => "forbidden",
+        StatusCode::NOT_FOUND => "not_found",
+        StatusCode::METHOD_NOT_ALLOWED => "method_not_allowed",
+        StatusCode::CONFLICT => "conflict",
+        StatusCode::PAYLOAD_TOO_LARGE => "payload_too_large",
+        StatusCode::UNSUPPORTED_MEDIA_TYPE => "unsupported_media_type",
+        StatusCode::UNPROCESSABLE_ENTITY => "unprocessable_entity",
+        StatusCode::TOO_MANY_REQUESTS => "rate_limited",
+        StatusCode::SERVICE_UNAVAILABLE => "service_unavailable",
+        status if status.is_server_error() => "internal_error",
+        _ => "error",
+    }
+}
+
+/// Titles by code, in en, de, es, fr
+const TITLES: &[(&str, [&str; 4])] = &[
+    ("bad_request", ["Bad request", "Ungültige Anfrage", "Solicitud incorrecta", "Requête invalide"]),
+    ("invalid_input", ["Invalid input", "Ungültige Eingabe", "Entrada no válida", "Saisie invalide"]),
+    ("validation_failed", ["Validation failed", "Validierung fehlgeschlagen", "La validación falló", "La validation a échoué"]),
+    ("invalid_json", ["Invalid JSON", "Ungültiges JSON", "JSON no válido", "JSON invalide"]),
+    ("unauthorized", ["Unauthorized", "Nicht angemeldet", "No autenticado", "Non authentifié"]),
+    ("invalid_token", ["Invalid token", "Ungültiges Token", "Token no válido", "Jeton invalide"]),
+    ("forbidden", ["Access denied", "Zugriff verweigert", "Acceso denegado", "Accès refusé"]),
+    ("csrf_failed", ["CSRF validation failed", "CSRF-Prüfung fehlgeschlagen", "La validación CSRF falló", "La vérification CSRF a échoué"]),
+    ("not_found", ["Not found", "Nicht gefunden", "No encontrado", "Introuvable"]),
+    ("method_not_allowed", ["Method not allowed", "Methode nicht erlaubt", "Método no permitido", "Méthode non autorisée"]),
+    ("conflict", ["Resource already exists", "Ressource existiert bereits", "El recurso ya existe", "La ressource existe déjà"]),
+    ("payload_too_large", ["Request body too large", "Anfrage zu groß", "Cuerpo de la solicitud demasiado grande", "Corps de la requête trop volumineux"]),
+    ("unsupported_media_type", ["Unsupported Content-Type", "Content-Type nicht unterstützt", "Content-Type no admitido", "Content-Type non pris en charge"]),
+    ("missing_content_type", ["Content-Type header required", "Content-Type-Header erforderlich", "Se requiere la cabecera Content-Type", "En-tête Content-Type requis"]),
+    ("unprocessable_entity", ["Request could not be processed", "Anfrage konnte nicht verarbeitet werden", "No se pudo procesar la solicitud", "La requête n'a pas pu être traitée"]),
+    ("quota_exceeded", ["Quota exceeded", "Kontingent überschritten", "Cuota excedida", "Quota dépassé"]),
+    ("rate_limited", ["Too many requests", "Zu viele Anfragen", "Demasiadas solicitudes", "Trop de requêtes"]),
+    ("internal_error", ["Internal server error", "Interner Serverfehler", "Error interno del servidor", "Erreur interne du serveur"]),
+    ("ai_output_invalid", ["AI response could not be validated", "KI-Antwort konnte nicht validiert werden", "No se pudo validar la respuesta de la IA", "La réponse de l'IA n'a pas pu être validée"]),
+    ("service_busy", ["Service is busy, please try again shortly", "Dienst ausgelastet, bitte gleich erneut versuchen", "Servicio ocupado, inténtelo de nuevo en breve", "Service occupé, veuillez réessayer sous peu"]),
+    ("service_unavailable", ["Service unavailable", "Dienst nicht verfügbar", "Servicio no disponible", "Service indisponible"]),
+    ("maintenance", ["Down for maintenance", "Wartungsarbeiten", "En mantenimiento", "Maintenance en cours"]),
+    ("query_budget_exceeded", ["The request took too long. Please narrow it down or try again shortly.", "Die Anfrage dauerte zu lange. Bitte eingrenzen oder gleich erneut versuchen.", "La solicitud tardó demasiado. Acótela o inténtelo de nuevo en breve.", "La requête a pris trop de temps. Affinez-la ou réessayez sous peu."]),
+];
+
+/// Title of `code` in `locale`
+pub fn title(code: &str, locale: Locale) -> &'static str {
+    let titles = TITLES
+        .iter()
+        .find(|(known, _)| *known == code)
+        .map(|(_, titles)| titles)
+        .unwrap_or(&["Request failed", "Anfrage fehlgeschlagen", "La solicitud falló", "La requête a échoué"]);
+    titles[locale as usize]
+}
+
+/// A bound as written in the validator (`1`, not `1.0`)
+fn bound(value: &Value) -> String {
+    match value.as_f64() {
+        Some(number) if number.fract() == 0.0 && number.abs() < 1e15 => format!("{}", number as i64),
+        _ => value.to_string(),
+    }
+}
+
+/// Message of a failed validator in `locale`
+fn field_message(code: &str, locale: Locale, min: Option<&Value>, max: Option<&Value>) -> String {
+    use Locale::*;
+    let (min, max) = (min.map(bound), max.map(bound));
+    match (code, min.as_deref(), max.as_deref()) {
+        ("length", Some(min), Some(max)) if min == max => match locale {
+            En => format!("must be exactly {} characters", min),
+            De => format!("muss genau {} Zeichen lang sein", min),
+            Es => format!("debe tener exactamente {} caracteres", min),
+            Fr => format!("doit contenir exactement {} caractères", min),
+        },
+        ("length", Some(min), Some(max)) => match locale {
+            En => format!("must be between {} and {} characters", min, max),
+            De => format!("muss zwischen {} und {} Zeichen lang sein", min, max),
+            Es => format!("debe tener entre {} y {} caracteres", min, max),
+            Fr => format!("doit contenir entre {} et {} caractères", min, max),
+        },
+        ("length", Some(min), None) => match locale {
+            En => format!("must be at least {} characters", min),
+            De => format!("muss mindestens {} Zeichen lang sein", min),
+            Es => format!("debe tener al menos {} caracteres", min),
+            Fr => format!("doit contenir au moins {} caractères", min),
+        },
+        ("length", None, Some(max)) => match locale {
+            En => format!("must be at most {} characters", max),
+            De => format!("darf höchstens {} Zeichen lang sein", max),
+            Es => format!("debe tener como máximo {} caracteres", max),
+            Fr => format!("doit contenir au plus {} caractères", max),
+        },
+        ("range", Some(min), Some(max)) => match locale {
+            En => format!("must be between {} and {}", min, max),
+            De => format!("muss zwischen {} und {} liegen", min, max),
+            Es => format!("debe estar entre {} y {}", min, max),
+            Fr => format!("doit être compris entre {} et {}", min, max),
+        },
+        ("range", Some(min), None) => match locale {
+            En => format!("must be at least {}", min),
+            De => format!("muss mindestens {} sein", min),
+            Es => format!("debe ser al menos {}", min),
+            Fr => format!("doit être au moins {}", min),
+        },
+        ("range", None, Some(max)) => match locale {
+            En => format!("must be at most {}", max),
+            De => format!("darf höchstens {} sein", max),
+            Es => format!("debe ser como máximo {}", max),
+            Fr => format!("doit être au plus {}", max),
+        },
+        ("email", _, _) => match locale {
+            En => "must be a valid email address",
+            De => "muss eine gültige E-Mail-Adresse sein",
+            Es => "debe ser un correo electrónico válido",
+            Fr => "doit être une adresse e-mail valide",
+        }
+        .to_string(),
+        ("url", _, _) => match locale {
+            En => "must be a valid URL",
+            De => "muss eine gültige URL sein",
+            Es => "debe ser una URL válida",
+            Fr => "doit être une URL valide",
+        }
+        .to_string(),
+        ("required", _, _) => match locale {
+            En => "is required",
+            De => "ist erforderlich",
+            Es => "es obligatorio",
+            Fr => "est obligatoire",
+        }
+        .to_string(),
+        _ => match locale {
+            En => "is invalid",
+            De => "ist ungültig",
+            Es => "no es válido",
+            Fr => "n'est pas valide",
+        }
+        .to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::middleware::request_id::{request_id_middleware, REQUEST_ID_HEADER};
+    use axum::{http::Request, routing::get, Router};
+    use tower::ServiceExt;
+    use validator::Validate;
+
+    #[derive(Validate)]
+    struct Item {
+        #[validate(range(min = 1))]
+        quantity: i32,
+    }
+
+    #[derive(Validate)]
+    struct Order {
+        #[validate(length(min = 2, max = 10))]
+        name: String,
+        #[validate(length(min = 1, message = "Batch number required"))]
+        batch_number: String,
+        #[validate(nested)]
+        items: Vec<Item>,
+    }
+
+    fn invalid_order() -> ValidationErrors {
+        Order {
+            name: "x".to_string(),
+            batch_number: String::new(),
+            items: vec![Item { quantity: 1 }, Item { quantity: 0 }],
+        }
+        .validate()
+        .unwrap_err()
+    }
+
+    #[test]
+    fn test_accept_language() {
+        assert_eq!(Locale::from_accept_language("de-DE,de;q=0.9,en;q=0.8"), Locale::De);
+        assert_eq!(Locale::from_accept_language("ja, fr;q=0.5, es;q=0.7"), Locale::Es);
+        assert_eq!(Locale::from_accept_language("fr;q=0"), Locale::En);
+        assert_eq!(Locale::from_accept_language("*"), Locale::En);
+        assert_eq!(Locale::from_accept_language(""), Locale::En);
+    }
+
+    #[test]
+    fn test_field_errors_paths_and_messages() {
+        let errors = field_errors(&invalid_order());
+        let fields: Vec<&str> = errors.iter().map(|error| error.field.as_str()).collect();
+        assert_eq!(fields, vec!["batch_number", "items[1].quantity", "name"]);
+
+        assert_eq!(errors[0].message, "Batch number required");
+        assert_eq!(errors[1].code, "range");
+        assert_eq!(errors[1].message, "must be at least 1");
+        assert_eq!(errors[2].message, "must be between 2 and 10 characters");
+    }
+
+    #[test]
+    fn test_problem_document() {
+        let problem = Problem::new(StatusCode::BAD_REQUEST, "validation_failed").with_field_errors(&invalid_order());
+        let request_id = Uuid::new_v4();
+        let body = problem.to_json(Locale::De, Some(request_id));
+
+        assert_eq!(body["type"], "urn:atlas-pharma:problem:validation_failed");
+        assert_eq!(body["title"], "Validierung fehlgeschlagen");
+        assert_eq!(body["status"], 400);
+        assert_eq!(body["request_id"], request_id.to_string());
+        assert_eq!(body["error"], "Validierung fehlgeschlagen");
+        assert!(body.get("detail").is_none());
+        // Developer messages are English; other locales use the validator's code
+        assert_eq!(body["errors"][0]["message"], "muss mindestens 1 Zeichen lang sein");
+        assert_eq!(body["errors"][2]["message"], "muss zwischen 2 und 10 Zeichen lang sein");
+    }
+
+    #[test]
+    fn test_detail_and_extensions() {
+        let body = Problem::new(StatusCode::NOT_FOUND, "not_found")
+            .with_detail("Inventory item not found")
+            .with_extension("feature", json!("import"))
+            .with_extension("status", json!(500))
+            .to_json(Locale::En, None);
+
+        assert_eq!(body["detail"], "Inventory item not found");
+        assert_eq!(body["error"], "Inventory item not found");
+        assert_eq!(body["feature"], "import");
+        // Extensions never replace standard members
+        assert_eq!(body["status"], 404);
+        assert!(body.get("request_id").is_none());
+    }
+
+    #[test]
+    fn test_codes_have_titles() {
+        for status in [400u16, 401, 403, 404, 405, 409, 413, 415, 422, 429, 500, 502, 503] {
+            let code = code_for_status(StatusCode::from_u16(status).unwrap());
+            assert_ne!(title(code, Locale::En), "Request failed", "{} has no title", code);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_middleware_converts_bare_status() {
+        let app = Router::new()
+            .route("/", get(|| async { StatusCode::UNAUTHORIZED }))
+            .route("/text", get(|| async { (StatusCode::BAD_REQUEST, "Failed to deserialize query string") }))
+            .route("/problem", get(|| async { Problem::new(StatusCode::CONFLICT, "conflict") }))
+            .layer(axum::middleware::from_fn(problem_details_middleware))
+            .layer(axum::middleware::from_fn(request_id_middleware));
+
+        let response = app
+            .clone()
+            .oneshot(Request::builder().uri("/").header(header::ACCEPT_LANGUAGE, "fr").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], PROBLEM_JSON);
+        assert_eq!(response.headers()[header::CONTENT_LANGUAGE], "fr");
+        let request_id = response.headers()[REQUEST_ID_HEADER].to_str().unwrap().to_string();
+        let body: Value = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap();
+        assert_eq!(body["code"], "unauthorized");
+        assert_eq!(body["title"], "Non authentifié");
+        assert_eq!(body["request_id"], request_id);
+
+        let response = app
+            .clone()
+            .oneshot(Request::builder().uri("/text").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body: Value = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap();
+        assert_eq!(body["code"], "bad_request");
+        assert_eq!(body["detail"], "Failed to deserialize query string");
+
+        let response = app
+            .oneshot(Request::builder().uri("/problem").header(header::ACCEPT_LANGUAGE, "es").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body: Value = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap();
+        assert_eq!(body["title"], "El recurso ya existe");
+        assert!(body["request_id"].is_string());
+    }
+
+    #[tokio::test]
+    async fn test_middleware_leaves_other_json_alone() {
+        let app = Router::new()
+            .route("/", get(|| async { (StatusCode::BAD_REQUEST, axum::Json(json!({"error": "invalid_client"}))) }))
+            .layer(axum::middleware::from_fn(problem_details_middleware));
+
+        let response = app.oneshot(Request::builder().uri("/").body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+        let body: Value = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap();
+        assert_eq!(body, json!({"error": "invalid_client"}));
+    }
+}
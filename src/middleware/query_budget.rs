@@ -0,0 +1,54 @@
+// ============================================================================
+// Query Budget Middleware - Per-Request Time Budget for Public Endpoints
+// ============================================================================
+//
+// Unauthenticated endpoints (public marketplace search, expiry alerts) can be
+// hit by anyone, so one expensive query pattern must not tie up pool
+// connections for the full statement timeout. Each request gets
+// PUBLIC_QUERY_BUDGET_MS (`PoolConfig::public_query_budget`); past it the
+// handler is dropped, which releases its connection and cancels its query, and
+// the client gets 503 with Retry-After.
+//
+// Budget overruns are counted in atlas_query_budget_exceeded_total.
+//
+// ============================================================================
+
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use super::metrics::{normalize_path, record_query_budget_exceeded};
+use super::problem_details::Problem;
+use crate::config::AppConfig;
+
+/// Retry-After of requests cut off at their budget
+const BUDGET_RETRY_AFTER_SECONDS: u32 = 5;
+
+pub async fn query_budget_middleware(
+    State(config): State<AppConfig>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let budget = config.pool.public_query_budget;
+    let path = normalize_path(request.uri().path());
+
+    match tokio::time::timeout(budget, next.run(request)).await {
+        Ok(response) => response,
+        Err(_) => {
+            tracing::warn!("⏱️  {} exceeded its query budget of {}ms", path, budget.as_millis());
+            record_query_budget_exceeded(&path);
+            budget_exceeded_response()
+        }
+    }
+}
+
+fn budget_exceeded_response() -> Response {
+    let mut response = Problem::new(StatusCode::SERVICE_UNAVAILABLE, "query_budget_exceeded").into_response();
+    response
+        .headers_mut()
+        .insert(header::RETRY_AFTER, HeaderValue::from(BUDGET_RETRY_AFTER_SECONDS));
+    response
+}
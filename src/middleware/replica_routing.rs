@@ -0,0 +1,47 @@
+// ============================================================================
+// Replica Routing Middleware - Read-Your-Writes Tracking
+// ============================================================================
+//
+// Remembers which users just wrote, so read-after-write endpoints
+// (`ReadConsistency::ReadYourWrites`) send their next reads to the primary
+// until the read replica has caught up. Every successful POST, PUT, PATCH or
+// DELETE with a valid token (cookie or bearer) counts as a write.
+//
+// Without a configured replica this passes straight through.
+//
+// ============================================================================
+
+use axum::{
+    extract::{Request, State},
+    http::Method,
+    middleware::Next,
+    response::Response,
+};
+
+use super::auth::peek_request_claims;
+use crate::config::AppConfig;
+
+pub async fn replica_routing_middleware(
+    State(config): State<AppConfig>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if config.read_replica.is_none() || !is_write(request.method()) {
+        return next.run(request).await;
+    }
+
+    let user_id = peek_request_claims(&config, request.headers()).map(|claims| claims.user_id);
+    let response = next.run(request).await;
+
+    if let Some(user_id) = user_id {
+        if response.status().is_success() || response.status().is_redirection() {
+            config.note_write(user_id);
+        }
+    }
+
+    response
+}
+
+fn is_write(method: &Method) -> bool {
+    matches!(*method, Method::POST | Method::PUT | Method::PATCH | Method::DELETE)
+}
@@ -68,7 +68,8 @@ pub const REQUEST_ID_HEADER: &str = "x-request-id";
 /// 2. If yes, validate and use it
 /// 3. If no, generate new UUID v4
 /// 4. Add request ID to response headers
-/// 5. Log request with request ID
+///
+/// Requests are logged by `request_logging_middleware`, which runs inside this one.
 ///
 pub async fn request_id_middleware(
     mut request: Request,
@@ -85,14 +86,6 @@ pub async fn request_id_middleware(
     // Store request ID in request extensions for use in handlers
     request.extensions_mut().insert(request_id);
 
-    // Log incoming request with request ID
-    tracing::info!(
-        request_id = %request_id,
-        method = %request.method(),
-        uri = %request.uri(),
-        "→ Incoming request"
-    );
-
     // Process request
     let mut response = next.run(request).await;
 
@@ -102,13 +95,6 @@ pub async fn request_id_middleware(
         request_id.to_string().parse().unwrap(),
     );
 
-    // Log outgoing response with request ID
-    tracing::info!(
-        request_id = %request_id,
-        status = %response.status(),
-        "← Outgoing response"
-    );
-
     response
 }
 
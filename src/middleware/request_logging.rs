@@ -0,0 +1,207 @@
+// ============================================================================
+// Request Logging Middleware - Structured, Sampled Request Logs
+// ============================================================================
+//
+// 📊 OBSERVABILITY: One structured event per request, replacing the free-form
+// "→ Incoming request" / "← Outgoing response" lines
+//
+// ## Fields:
+//
+// - request_id: from `request_id_middleware` (must run outside this layer)
+// - user_id: recorded on the request span by the auth middlewares once the
+//   token is validated (see `record_request_user`)
+// - method, route: the route template (`/api/inventory/:id`), never the raw
+//   path, so IDs and query strings stay out of the logs
+// - status, latency_ms
+//
+// Every event logged while handling the request carries the span's
+// request_id and user_id as well.
+//
+// ## Sampling:
+//
+// High-volume routes (health probes, metrics scrapes) would drown everything
+// else. Successful requests on those routes are logged at their sample rate;
+// errors (status >= 400) and slow requests are always logged. Sampling is
+// keyed on the request id, so a request is either logged or not everywhere.
+//
+// - LOG_SAMPLE_RATES: `route=rate` pairs, comma separated, that extend or
+//   override the defaults (e.g. `/api/marketplace/search=0.25,/health=0`)
+// - LOG_SLOW_REQUEST_MS: requests slower than this are always logged (1000)
+//
+// ## Redaction:
+//
+// The log writer passes every formatted line through `log_sanitizer`
+// (see `RedactingMakeWriter`), so PII in any event is masked before output.
+//
+// ============================================================================
+
+use axum::{
+    extract::{MatchedPath, Request},
+    middleware::Next,
+    response::Response,
+};
+use once_cell::sync::Lazy;
+use std::{collections::HashMap, time::Instant};
+use tracing::Instrument;
+use uuid::Uuid;
+
+use super::request_id::get_request_id;
+
+/// Sample rates of routes that are hit far more often than the rest
+const DEFAULT_SAMPLE_RATES: &[(&str, f64)] = &[
+    ("/metrics", 0.0),
+    ("/health/live", 0.0),
+    ("/health/ready", 0.01),
+    ("/health", 0.01),
+];
+
+const DEFAULT_SLOW_REQUEST_MS: u128 = 1000;
+
+static SAMPLING: Lazy<LogSampling> = Lazy::new(LogSampling::from_env);
+
+/// Per-route sample rates for successful requests
+#[derive(Debug, Clone)]
+pub struct LogSampling {
+    /// Share of successful requests logged per route template, 0.0 to 1.0
+    rates: HashMap<String, f64>,
+    /// Requests at least this slow are always logged
+    slow_request_ms: u128,
+}
+
+impl LogSampling {
+    pub fn from_env() -> Self {
+        let mut sampling = Self::default();
+        if let Ok(rates) = std::env::var("LOG_SAMPLE_RATES") {
+            sampling.apply_overrides(&rates);
+        }
+        if let Some(ms) = std::env::var("LOG_SLOW_REQUEST_MS").ok().and_then(|ms| ms.parse().ok()) {
+            sampling.slow_request_ms = ms;
+        }
+        sampling
+    }
+
+    /// Apply `route=rate` pairs; malformed pairs are skipped with a warning
+    pub fn apply_overrides(&mut self, spec: &str) {
+        for pair in spec.split(',').map(str::trim).filter(|pair| !pair.is_empty()) {
+            match pair.split_once('=').map(|(route, rate)| (route.trim(), rate.trim().parse::<f64>())) {
+                Some((route, Ok(rate))) if route.starts_with('/') => {
+                    self.rates.insert(route.to_string(), rate.clamp(0.0, 1.0));
+                }
+                _ => tracing::warn!("Ignoring malformed LOG_SAMPLE_RATES entry '{}'", pair),
+            }
+        }
+    }
+
+    pub fn rate(&self, route: &str) -> f64 {
+        self.rates.get(route).copied().unwrap_or(1.0)
+    }
+
+    /// Errors and slow requests are always logged; the rest by their route's rate
+    pub fn should_log(&self, route: &str, status: u16, latency_ms: u128, request_id: Uuid) -> bool {
+        if status >= 400 || latency_ms >= self.slow_request_ms {
+            return true;
+        }
+        let rate = self.rate(route);
+        if rate >= 1.0 {
+            return true;
+        }
+        // Same decision for the same request id, spread evenly over the id space
+        (request_id.as_u128() % 10_000) < (rate * 10_000.0) as u128
+    }
+}
+
+impl Default for LogSampling {
+    fn default() -> Self {
+        Self {
+            rates: DEFAULT_SAMPLE_RATES
+                .iter()
+                .map(|(route, rate)| (route.to_string(), *rate))
+                .collect(),
+            slow_request_ms: DEFAULT_SLOW_REQUEST_MS,
+        }
+    }
+}
+
+/// Record the authenticated user on the current request span and error tracking scope
+pub fn record_request_user(user_id: Uuid) {
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+    super::error_tracking::set_request_user(user_id);
+}
+
+/// Log one structured event per request
+///
+/// Runs inside `request_id_middleware`; the route template is only known once
+/// the router has matched, otherwise the path is normalized like the metrics do.
+pub async fn request_logging_middleware(request: Request, next: Next) -> Response {
+    let request_id = get_request_id(request.extensions()).unwrap_or_else(Uuid::new_v4);
+    let method = request.method().clone();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| super::metrics::normalize_path(request.uri().path()));
+
+    let span = tracing::info_span!(
+        "request",
+        request_id = %request_id,
+        user_id = tracing::field::Empty,
+    );
+
+    let started = Instant::now();
+    let response = next.run(request).instrument(span.clone()).await;
+    let latency_ms = started.elapsed().as_millis();
+    let status = response.status().as_u16();
+
+    if SAMPLING.should_log(&route, status, latency_ms, request_id) {
+        let _entered = span.enter();
+        if status >= 500 {
+            tracing::error!(method = %method, route = %route, status, latency_ms = latency_ms as u64, "request completed");
+        } else if status >= 400 {
+            tracing::warn!(method = %method, route = %route, status, latency_ms = latency_ms as u64, "request completed");
+        } else {
+            tracing::info!(method = %method, route = %route, status, latency_ms = latency_ms as u64, "request completed");
+        }
+    }
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_errors_and_slow_requests_always_logged() {
+        let sampling = LogSampling::default();
+        let request_id = Uuid::new_v4();
+
+        assert!(!sampling.should_log("/health/live", 200, 2, request_id));
+        assert!(sampling.should_log("/health/live", 503, 2, request_id));
+        assert!(sampling.should_log("/health/live", 200, 5000, request_id));
+        assert!(sampling.should_log("/api/inventory/:id", 200, 2, request_id));
+    }
+
+    #[test]
+    fn test_sampling_is_keyed_on_request_id() {
+        let mut sampling = LogSampling::default();
+        sampling.apply_overrides("/api/marketplace/search=0.5");
+
+        let logged = Uuid::from_u128(1234);
+        let skipped = Uuid::from_u128(9_999);
+        assert!(sampling.should_log("/api/marketplace/search", 200, 2, logged));
+        assert!(sampling.should_log("/api/marketplace/search", 200, 2, logged));
+        assert!(!sampling.should_log("/api/marketplace/search", 200, 2, skipped));
+    }
+
+    #[test]
+    fn test_overrides() {
+        let mut sampling = LogSampling::default();
+        sampling.apply_overrides("/health=1, /api/search = 0.2, broken, /x=abc, /y=7");
+
+        assert_eq!(sampling.rate("/health"), 1.0);
+        assert_eq!(sampling.rate("/api/search"), 0.2);
+        assert_eq!(sampling.rate("/y"), 1.0);
+        assert_eq!(sampling.rate("/x"), 1.0);
+        assert_eq!(sampling.rate("/metrics"), 0.0);
+    }
+}
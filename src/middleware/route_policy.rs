@@ -0,0 +1,507 @@
+// ============================================================================
+// Route Policy Audit - Which Middleware Protects Each Route
+// ============================================================================
+//
+// 🔒 SECURITY: axum can't list a built router's routes or layers, so the audit
+// walks the router definition in main.rs instead: `Router::new()` chains of
+// `.route`, `.nest`, `.merge` and `.layer`, where a layer wraps every route added
+// before it in the same chain. The result lists each route (method + path
+// template) with the protection it is wrapped in:
+//
+// - auth: `auth_middleware` (valid, unrevoked session)
+// - admin / superadmin: `admin_middleware` / `superadmin_middleware`
+// - rate_limit: `rate_limit_middleware` (every route, via the global stack)
+// - auth_rate_limit: the stricter limiter of the public auth endpoints
+//
+// ## Rules:
+//
+// 1. Every route requires auth unless it is listed in `PUBLIC_ROUTES` with the
+//    reason it is public
+// 2. Every route under /api/admin additionally requires admin or superadmin
+//    (public ones included only via `PUBLIC_ROUTES`)
+// 3. Public routes marked `rate_limited` must have the auth rate limiter
+// 4. Every `PUBLIC_ROUTES` entry must still match a route, so the list can't
+//    silently keep a removed route public when the path is reused
+//
+// The report is built at startup (violations are logged; ROUTE_POLICY_STRICT=true
+// refuses to start), served at GET /api/admin/route-policies, and checked by
+// tests/route_policies.rs so a new unprotected route fails CI.
+//
+// ============================================================================
+
+use once_cell::sync::{Lazy, OnceCell};
+use regex::Regex;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RoutePolicy {
+    Auth,
+    Admin,
+    Superadmin,
+    RateLimit,
+    AuthRateLimit,
+}
+
+impl RoutePolicy {
+    /// Identifier in a `.layer(...)` that applies the policy
+    fn marker(&self) -> &'static str {
+        match self {
+            RoutePolicy::Auth => "auth_middleware",
+            RoutePolicy::Admin => "admin_middleware",
+            RoutePolicy::Superadmin => "superadmin_middleware",
+            RoutePolicy::RateLimit => "rate_limit_middleware",
+            RoutePolicy::AuthRateLimit => "auth_rate_limiter",
+        }
+    }
+
+    const ALL: [RoutePolicy; 5] = [
+        RoutePolicy::Auth,
+        RoutePolicy::Admin,
+        RoutePolicy::Superadmin,
+        RoutePolicy::RateLimit,
+        RoutePolicy::AuthRateLimit,
+    ];
+}
+
+/// A route that is intentionally reachable without a session
+#[derive(Debug)]
+pub struct PublicRoute {
+    pub method: &'static str,
+    pub path: &'static str,
+    pub reason: &'static str,
+    /// Whether it must sit behind the auth rate limiter (credential endpoints)
+    pub rate_limited: bool,
+}
+
+pub const PUBLIC_ROUTES: &[PublicRoute] = &[
+    PublicRoute { method: "POST", path: "/api/auth/register", reason: "Account sign-up", rate_limited: true },
+    PublicRoute { method: "POST", path: "/api/auth/login", reason: "Sign-in", rate_limited: true },
+    PublicRoute { method: "POST", path: "/api/auth/refresh", reason: "Token refresh; validates the refresh token itself", rate_limited: true },
+    PublicRoute { method: "GET", path: "/api/auth/oauth/providers", reason: "Sign-in page lists the enabled OAuth providers", rate_limited: false },
+    PublicRoute { method: "GET", path: "/api/auth/oauth/:provider", reason: "Redirect to the OAuth provider", rate_limited: false },
+    PublicRoute { method: "GET", path: "/api/auth/oauth/:provider/callback", reason: "OAuth provider callback; verifies the state parameter", rate_limited: false },
+    PublicRoute { method: "GET", path: "/api/admin/health", reason: "Monitoring probe", rate_limited: false },
+    PublicRoute { method: "GET", path: "/api/admin/health/ready", reason: "Load balancer readiness probe", rate_limited: false },
+    PublicRoute { method: "GET", path: "/api/admin/health/live", reason: "Liveness probe", rate_limited: false },
+    PublicRoute { method: "GET", path: "/api/pharmaceuticals/:id/images", reason: "Approved product images are public (CDN-cacheable)", rate_limited: false },
+    PublicRoute { method: "GET", path: "/api/pharmaceuticals/:id/images/:image_id/file", reason: "Approved product images are public (CDN-cacheable)", rate_limited: false },
+    PublicRoute { method: "GET", path: "/api/pharmaceuticals/:id/images/:image_id/thumbnail", reason: "Approved product images are public (CDN-cacheable)", rate_limited: false },
+    PublicRoute { method: "GET", path: "/api/public/inventory/search", reason: "Public marketplace preview", rate_limited: false },
+    PublicRoute { method: "GET", path: "/api/public/expiry-alerts", reason: "Public marketplace preview", rate_limited: false },
+    PublicRoute { method: "POST", path: "/api/erp/webhooks/netsuite/:id", reason: "ERP webhook; verified by HMAC signature", rate_limited: false },
+    PublicRoute { method: "POST", path: "/api/erp/webhooks/sap/:id", reason: "ERP webhook; verified by HMAC signature", rate_limited: false },
+    PublicRoute { method: "POST", path: "/api/edi/as2/:partner_id", reason: "AS2 receipt; partners authenticate with their inbound token", rate_limited: false },
+    PublicRoute { method: "GET", path: "/api/exports/:id/download", reason: "Data export download; authorized by an expiring signed link", rate_limited: false },
+    PublicRoute { method: "GET", path: "/api/openapi.json", reason: "OpenAPI description of the API, read by Swagger UI and client generators", rate_limited: false },
+    PublicRoute { method: "GET", path: "/fhir/metadata", reason: "FHIR capability statement, read by clients before they authenticate", rate_limited: false },
+    PublicRoute { method: "GET", path: "/fhir/.well-known/smart-configuration", reason: "SMART discovery of the FHIR token endpoint", rate_limited: false },
+    PublicRoute { method: "POST", path: "/fhir/auth/token", reason: "FHIR client token exchange; verifies the client credentials", rate_limited: true },
+    PublicRoute { method: "GET", path: "/metrics", reason: "Prometheus scrape endpoint", rate_limited: false },
+];
+
+impl PublicRoute {
+    pub fn find(method: &str, path: &str) -> Option<&'static PublicRoute> {
+        PUBLIC_ROUTES.iter().find(|route| route.method == method && route.path == path)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct RouteEntry {
+    pub method: String,
+    pub path: String,
+    pub policies: Vec<RoutePolicy>,
+    /// Why the route is public, when it is listed in `PUBLIC_ROUTES`
+    #[schema(value_type = Option<String>)]
+    pub public_reason: Option<&'static str>,
+}
+
+impl RouteEntry {
+    pub fn has(&self, policy: RoutePolicy) -> bool {
+        self.policies.contains(&policy)
+    }
+
+    fn is_authenticated(&self) -> bool {
+        self.has(RoutePolicy::Auth) || self.is_admin_only()
+    }
+
+    fn is_admin_only(&self) -> bool {
+        self.has(RoutePolicy::Admin) || self.has(RoutePolicy::Superadmin)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct RoutePolicyViolation {
+    pub method: String,
+    pub path: String,
+    #[schema(value_type = String)]
+    pub rule: &'static str,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct RoutePolicyReport {
+    pub route_count: usize,
+    pub routes: Vec<RouteEntry>,
+    pub violations: Vec<RoutePolicyViolation>,
+}
+
+static REPORT: OnceCell<RoutePolicyReport> = OnceCell::new();
+
+/// Keep the startup report for the admin endpoint
+pub fn install_route_policy_report(report: RoutePolicyReport) {
+    let _ = REPORT.set(report);
+}
+
+pub fn route_policy_report() -> Option<&'static RoutePolicyReport> {
+    REPORT.get()
+}
+
+impl RoutePolicyReport {
+    /// Walk the router built as `let app = Router::new()...` in `source` and check
+    /// every route against the rules
+    pub fn from_source(source: &str) -> Self {
+        let source = strip_line_comments(source);
+        let routes = source
+            .find("let app = ")
+            .map(|start| walk_router(&source[start + "let app = ".len()..]))
+            .unwrap_or_default();
+
+        let mut routes: Vec<RouteEntry> = routes
+            .into_iter()
+            .map(|(method, path, policies)| RouteEntry {
+                public_reason: PublicRoute::find(&method, &path).map(|route| route.reason),
+                method,
+                path,
+                policies,
+            })
+            .collect();
+        routes.sort_by(|a, b| a.path.cmp(&b.path).then_with(|| a.method.cmp(&b.method)));
+
+        let violations = check(&routes);
+        Self { route_count: routes.len(), routes, violations }
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+fn check(routes: &[RouteEntry]) -> Vec<RoutePolicyViolation> {
+    let mut violations = Vec::new();
+    let mut violation = |method: &str, path: &str, rule: &'static str, message: String| {
+        violations.push(RoutePolicyViolation { method: method.to_string(), path: path.to_string(), rule, message });
+    };
+
+    if routes.is_empty() {
+        violation("*", "*", "router_not_found", "No routes found in the router definition".to_string());
+    }
+
+    for route in routes {
+        let public = PublicRoute::find(&route.method, &route.path);
+
+        if public.is_none() && !route.is_authenticated() {
+            violation(&route.method, &route.path, "auth_required",
+                "Route is not wrapped in auth_middleware and is not listed in PUBLIC_ROUTES".to_string());
+        }
+        if (route.path == "/api/admin" || route.path.starts_with("/api/admin/")) && public.is_none() && !route.is_admin_only() {
+            violation(&route.method, &route.path, "admin_required",
+                "Admin route is not wrapped in admin_middleware or superadmin_middleware".to_string());
+        }
+        if public.is_some_and(|public| public.rate_limited) && !route.has(RoutePolicy::AuthRateLimit) {
+            violation(&route.method, &route.path, "rate_limit_required",
+                "Public credential endpoint is not behind the auth rate limiter".to_string());
+        }
+    }
+
+    for public in PUBLIC_ROUTES {
+        if !routes.iter().any(|route| route.method == public.method && route.path == public.path) {
+            violation(public.method, public.path, "stale_public_route",
+                "PUBLIC_ROUTES entry matches no route; remove it".to_string());
+        }
+    }
+
+    violations
+}
+
+// ============================================================================
+// Router Definition Walker
+// ============================================================================
+
+type WalkedRoute = (String, String, Vec<RoutePolicy>);
+
+static METHOD_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r"\b(get|post|put|delete|patch)\s*\(").unwrap());
+
+static POLICY_PATTERNS: Lazy<Vec<(RoutePolicy, Regex)>> = Lazy::new(|| {
+    RoutePolicy::ALL
+        .iter()
+        .map(|policy| (*policy, Regex::new(&format!(r"\b{}\b", policy.marker())).unwrap()))
+        .collect()
+});
+
+/// Routes of a `Router::new()...` chain expression, with their policies
+fn walk_router(expr: &str) -> Vec<WalkedRoute> {
+    let mut routes: Vec<WalkedRoute> = Vec::new();
+
+    for (name, args) in method_chain(expr) {
+        match name {
+            "route" => {
+                let args = split_args(args);
+                if let (Some(path), Some(method_router)) = (args.first().and_then(|a| string_literal(a)), args.get(1)) {
+                    for method in METHOD_PATTERN.captures_iter(method_router) {
+                        routes.push((method[1].to_uppercase(), path.to_string(), Vec::new()));
+                    }
+                }
+            }
+            "nest" => {
+                let args = split_args(args);
+                if let (Some(prefix), Some(router)) = (args.first().and_then(|a| string_literal(a)), args.get(1)) {
+                    for (method, path, policies) in walk_router(router) {
+                        let path = if path == "/" { prefix.to_string() } else { format!("{}{}", prefix, path) };
+                        routes.push((method, path, policies));
+                    }
+                }
+            }
+            "merge" => routes.extend(walk_router(args)),
+            "layer" | "route_layer" => {
+                let policies: Vec<RoutePolicy> = POLICY_PATTERNS
+                    .iter()
+                    .filter(|(_, pattern)| pattern.is_match(args))
+                    .map(|(policy, _)| *policy)
+                    .collect();
+                for (_, _, route_policies) in routes.iter_mut() {
+                    for policy in &policies {
+                        if !route_policies.contains(policy) {
+                            route_policies.push(*policy);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    routes
+}
+
+/// `.name(args)` calls of a chain starting with `Router::new()`
+fn method_chain(expr: &str) -> Vec<(&str, &str)> {
+    let expr = expr.trim_start();
+    let Some(mut rest) = expr.strip_prefix("Router::new()") else {
+        return Vec::new();
+    };
+
+    let mut calls = Vec::new();
+    loop {
+        rest = rest.trim_start();
+        let Some(after_dot) = rest.strip_prefix('.') else { break };
+        let name_len = after_dot
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(after_dot.len());
+        let (name, after_name) = after_dot.split_at(name_len);
+        if !after_name.starts_with('(') {
+            break;
+        }
+        let Some(close) = matching_paren(after_name) else { break };
+        calls.push((name, &after_name[1..close]));
+        rest = &after_name[close + 1..];
+    }
+    calls
+}
+
+/// Index of the `)` closing the `(` at the start of `s`
+fn matching_paren(s: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in s.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Top-level comma separated arguments
+fn split_args(args: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut start = 0;
+    for (i, c) in args.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(args[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(args[start..].trim());
+    parts.retain(|part| !part.is_empty());
+    parts
+}
+
+fn string_literal(arg: &str) -> Option<&str> {
+    arg.strip_prefix('"')?.strip_suffix('"')
+}
+
+/// Drop `//` comments (outside string literals); route comments are free text
+fn strip_line_comments(source: &str) -> String {
+    let mut output = String::with_capacity(source.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut chars = source.chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            output.push(c);
+            continue;
+        }
+        if c == '/' && chars.peek() == Some(&'/') {
+            while chars.peek().is_some_and(|next| *next != '\n') {
+                chars.next();
+            }
+            continue;
+        }
+        if c == '"' {
+            in_string = true;
+        }
+        output.push(c);
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"
+        let app = Router::new()
+            .nest(
+                "/api/auth",
+                Router::new()
+                    .route("/login", post(login)) // public (rate limited)
+                    .layer(middleware::from_fn(rate_limit_middleware))
+                    .layer(axum::Extension(auth_rate_limiter.clone()))
+                    .merge(
+                        Router::new()
+                            .route("/profile", get(get_profile).put(update_profile))
+                            .layer(middleware::from_fn_with_state(config.clone(), auth_middleware))
+                    )
+            )
+            .nest(
+                "/api/admin",
+                Router::new()
+                    .route("/health", get(health_check))
+                    .route("/users", get(list_users))
+                    .route("/secrets", get(secrets))
+                    .layer(middleware::from_fn_with_state(config.clone(), auth_middleware))
+                    .route("/", get(dashboard))
+                    .layer(middleware::from_fn(superadmin_middleware))
+            )
+            .route("/metrics", get(metrics_handler));
+    "#;
+
+    fn route<'a>(report: &'a RoutePolicyReport, method: &str, path: &str) -> &'a RouteEntry {
+        report.routes.iter().find(|r| r.method == method && r.path == path).unwrap()
+    }
+
+    #[test]
+    fn test_walk_applies_layers_to_earlier_routes() {
+        let report = RoutePolicyReport::from_source(SOURCE);
+        assert_eq!(report.route_count, 8);
+
+        let login = route(&report, "POST", "/api/auth/login");
+        assert_eq!(login.policies, vec![RoutePolicy::RateLimit, RoutePolicy::AuthRateLimit]);
+        assert_eq!(login.public_reason, Some("Sign-in"));
+
+        // Merged after the rate limit layers, so only its own layer applies
+        assert_eq!(route(&report, "PUT", "/api/auth/profile").policies, vec![RoutePolicy::Auth]);
+
+        // Nested "/" is the prefix itself; superadmin_middleware is not admin_middleware
+        assert_eq!(route(&report, "GET", "/api/admin").policies, vec![RoutePolicy::Superadmin]);
+        assert_eq!(
+            route(&report, "GET", "/api/admin/users").policies,
+            vec![RoutePolicy::Auth, RoutePolicy::Superadmin]
+        );
+    }
+
+    #[test]
+    fn test_violations() {
+        let report = RoutePolicyReport::from_source(SOURCE);
+        let rules: Vec<(&str, &str)> = report
+            .violations
+            .iter()
+            .map(|v| (v.path.as_str(), v.rule))
+            .collect();
+
+        // Listed as public: no violation for health, login, metrics
+        assert!(!rules.iter().any(|(path, _)| *path == "/api/admin/health" || *path == "/metrics"));
+        assert!(!rules.iter().any(|(path, _)| *path == "/api/auth/login"));
+        // Every other PUBLIC_ROUTES entry is missing from this router
+        assert!(rules.contains(&("/api/public/expiry-alerts", "stale_public_route")));
+        assert!(report.violations.iter().all(|v| v.rule == "stale_public_route"));
+    }
+
+    #[test]
+    fn test_unprotected_routes_flagged() {
+        let source = r#"
+            let app = Router::new()
+                .route("/api/orders", get(list_orders))
+                .nest("/api/admin", Router::new()
+                    .route("/export", get(export_everything))
+                    .layer(middleware::from_fn_with_state(config.clone(), auth_middleware)))
+                .nest("/api/auth", Router::new().route("/login", post(login)));
+        "#;
+        let report = RoutePolicyReport::from_source(source);
+        let flagged = |path: &str, rule: &str| report.violations.iter().any(|v| v.path == path && v.rule == rule);
+
+        assert!(flagged("/api/orders", "auth_required"));
+        assert!(flagged("/api/admin/export", "admin_required"));
+        assert!(!flagged("/api/admin/export", "auth_required"));
+        assert!(flagged("/api/auth/login", "rate_limit_required"));
+    }
+
+    #[test]
+    fn test_missing_router_is_a_violation() {
+        let report = RoutePolicyReport::from_source("fn main() {}");
+        assert_eq!(report.violations[0].rule, "router_not_found");
+    }
+}
@@ -0,0 +1,171 @@
+/// Accounting connector models (QuickBooks Online / Xero)
+///
+/// A seller connects an accounting system over OAuth2. Each completed marketplace sale
+/// is pushed as an invoice billed to the configured customer/contact and, when payments
+/// are recorded, a payment applied to that invoice.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+// ============================================================================
+// ENUMS
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AccountingProvider {
+    QuickBooks,
+    Xero,
+}
+
+impl AccountingProvider {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AccountingProvider::QuickBooks => "quickbooks",
+            AccountingProvider::Xero => "xero",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "quickbooks" => Some(AccountingProvider::QuickBooks),
+            "xero" => Some(AccountingProvider::Xero),
+            _ => None,
+        }
+    }
+}
+
+// ============================================================================
+// CONNECTIONS
+// ============================================================================
+
+#[derive(Debug, Clone, FromRow)]
+pub struct AccountingConnection {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub provider: String,
+    pub connection_name: String,
+    pub status: String,
+    pub sandbox: bool,
+    pub client_id: String,               // Encrypted
+    pub client_secret: String,           // Encrypted
+    pub access_token: Option<String>,    // Encrypted
+    pub refresh_token: Option<String>,   // Encrypted
+    pub token_expires_at: Option<DateTime<Utc>>,
+    pub tenant_id: Option<String>,
+    pub export_invoices: bool,
+    pub record_payments: bool,
+    pub customer_ref: Option<String>,
+    pub item_ref: Option<String>,
+    pub payment_account_ref: Option<String>,
+    pub last_error: Option<String>,
+    pub last_export_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct CreateAccountingConnectionRequest {
+    pub provider: AccountingProvider,
+    #[validate(length(min = 1, max = 255))]
+    pub connection_name: String,
+    #[validate(length(min = 1, max = 255))]
+    pub client_id: String,
+    #[validate(length(min = 1, max = 255))]
+    pub client_secret: String,
+    /// QuickBooks sandbox company (ignored for Xero)
+    pub sandbox: Option<bool>,
+}
+
+/// Export settings; PUT replaces all of them
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct UpdateAccountingSettingsRequest {
+    pub export_invoices: bool,
+    pub record_payments: bool,
+    #[validate(length(min = 1, max = 100))]
+    pub customer_ref: Option<String>,
+    #[validate(length(min = 1, max = 100))]
+    pub item_ref: Option<String>,
+    #[validate(length(min = 1, max = 100))]
+    pub payment_account_ref: Option<String>,
+}
+
+/// Redirect parameters handed back by the frontend after the user approved access
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CompleteAccountingAuthorizationRequest {
+    pub code: String,
+    pub state: String,
+    /// QuickBooks company ID (`realmId` query parameter of the redirect)
+    pub realm_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AccountingConnectionResponse {
+    pub id: Uuid,
+    pub provider: String,
+    pub connection_name: String,
+    pub status: String,
+    pub sandbox: bool,
+    pub tenant_id: Option<String>,
+    pub export_invoices: bool,
+    pub record_payments: bool,
+    pub customer_ref: Option<String>,
+    pub item_ref: Option<String>,
+    pub payment_account_ref: Option<String>,
+    pub last_error: Option<String>,
+    pub last_export_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<&AccountingConnection> for AccountingConnectionResponse {
+    fn from(connection: &AccountingConnection) -> Self {
+        Self {
+            id: connection.id,
+            provider: connection.provider.clone(),
+            connection_name: connection.connection_name.clone(),
+            status: connection.status.clone(),
+            sandbox: connection.sandbox,
+            tenant_id: connection.tenant_id.clone(),
+            export_invoices: connection.export_invoices,
+            record_payments: connection.record_payments,
+            customer_ref: connection.customer_ref.clone(),
+            item_ref: connection.item_ref.clone(),
+            payment_account_ref: connection.payment_account_ref.clone(),
+            last_error: connection.last_error.clone(),
+            last_export_at: connection.last_export_at,
+            created_at: connection.created_at,
+            updated_at: connection.updated_at,
+        }
+    }
+}
+
+/// Where to send the user to grant access
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AccountingAuthorizationResponse {
+    pub connection: AccountingConnectionResponse,
+    pub authorization_url: String,
+}
+
+// ============================================================================
+// EXPORTS
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct AccountingExport {
+    pub id: Uuid,
+    pub accounting_connection_id: Uuid,
+    pub transaction_id: Uuid,
+    pub status: String,
+    pub invoice_id: Option<String>,
+    pub invoice_number: Option<String>,
+    pub payment_id: Option<String>,
+    pub attempts: i32,
+    pub error_message: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
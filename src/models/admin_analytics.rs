@@ -0,0 +1,363 @@
+/// Admin analytics: signup funnels by cohort, active sellers/buyers and GMV,
+/// bucketed by day, week or month, as JSON or CSV
+
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use sqlx::FromRow;
+use crate::middleware::error_handling::{AppError, Result};
+
+/// Buckets in a range when `from` is not given
+pub const DEFAULT_BUCKETS: i64 = 12;
+
+/// Most buckets a single query may span
+pub const MAX_BUCKETS: i64 = 400;
+
+// ============================================================================
+// Time Buckets
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AnalyticsInterval {
+    Day,
+    Week,
+    Month,
+}
+
+impl AnalyticsInterval {
+    /// Postgres `date_trunc` unit; also the unit of the series step (`1 week`)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AnalyticsInterval::Day => "day",
+            AnalyticsInterval::Week => "week",
+            AnalyticsInterval::Month => "month",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "day" | "daily" => Some(AnalyticsInterval::Day),
+            "week" | "weekly" => Some(AnalyticsInterval::Week),
+            "month" | "monthly" => Some(AnalyticsInterval::Month),
+            _ => None,
+        }
+    }
+
+    /// Approximate length, used for default ranges and the bucket limit
+    pub fn approx_duration(&self) -> Duration {
+        match self {
+            AnalyticsInterval::Day => Duration::days(1),
+            AnalyticsInterval::Week => Duration::weeks(1),
+            AnalyticsInterval::Month => Duration::days(31),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnalyticsFormat {
+    Json,
+    Csv,
+}
+
+/// Query string of every analytics endpoint
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AnalyticsQuery {
+    /// Start of the range, inclusive (default: `DEFAULT_BUCKETS` intervals before `to`)
+    pub from: Option<DateTime<Utc>>,
+    /// End of the range, exclusive (default: now)
+    pub to: Option<DateTime<Utc>>,
+    /// day, week (default) or month
+    pub interval: Option<String>,
+    /// json (default) or csv
+    pub format: Option<String>,
+}
+
+/// A validated analytics query
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnalyticsRange {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub interval: AnalyticsInterval,
+    pub format: AnalyticsFormat,
+}
+
+impl AnalyticsQuery {
+    pub fn resolve(&self, now: DateTime<Utc>) -> std::result::Result<AnalyticsRange, String> {
+        let interval = match self.interval.as_deref() {
+            None => AnalyticsInterval::Week,
+            Some(value) => AnalyticsInterval::parse(value)
+                .ok_or_else(|| format!("Unknown interval '{}'. Use day, week or month", value))?,
+        };
+        let format = match self.format.as_deref().map(|f| f.trim().to_lowercase()).as_deref() {
+            None | Some("") | Some("json") => AnalyticsFormat::Json,
+            Some("csv") => AnalyticsFormat::Csv,
+            Some(other) => return Err(format!("Unsupported format '{}'. Use json or csv", other)),
+        };
+
+        let to = self.to.unwrap_or(now);
+        let from = self.from.unwrap_or(to - interval.approx_duration() * DEFAULT_BUCKETS as i32);
+        if from >= to {
+            return Err("from must be before to".to_string());
+        }
+        if (to - from).num_seconds() / interval.approx_duration().num_seconds() > MAX_BUCKETS {
+            return Err(format!(
+                "Range too long for {} buckets (max {} buckets); use a larger interval",
+                interval.as_str(),
+                MAX_BUCKETS
+            ));
+        }
+
+        Ok(AnalyticsRange { from, to, interval, format })
+    }
+}
+
+// ============================================================================
+// Rows
+// ============================================================================
+
+/// A row that can be exported as CSV
+pub trait AnalyticsRow {
+    const COLUMNS: &'static [&'static str];
+
+    /// Values in `COLUMNS` order
+    fn csv_values(&self) -> Vec<String>;
+}
+
+/// CSV with a header row
+pub fn encode_csv<T: AnalyticsRow>(rows: &[T]) -> Result<Vec<u8>> {
+    let csv_error = |e: csv::Error| AppError::Internal(anyhow::anyhow!("Failed to encode CSV: {}", e));
+
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(T::COLUMNS).map_err(csv_error)?;
+    for row in rows {
+        writer.write_record(row.csv_values()).map_err(csv_error)?;
+    }
+    writer
+        .into_inner()
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to encode CSV: {}", e)))
+}
+
+/// Accounts registered in one bucket and how far they got. Stages are counted
+/// independently: buyers transact without ever listing.
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct FunnelCohort {
+    pub cohort: DateTime<Utc>,
+    pub registered: i64,
+    pub verified: i64,
+    pub listed: i64,
+    pub transacted: i64,
+    pub median_hours_to_verification: Option<f64>,
+    pub median_hours_to_first_listing: Option<f64>,
+    pub median_hours_to_first_transaction: Option<f64>,
+}
+
+impl AnalyticsRow for FunnelCohort {
+    const COLUMNS: &'static [&'static str] = &[
+        "cohort",
+        "registered",
+        "verified",
+        "listed",
+        "transacted",
+        "median_hours_to_verification",
+        "median_hours_to_first_listing",
+        "median_hours_to_first_transaction",
+    ];
+
+    fn csv_values(&self) -> Vec<String> {
+        vec![
+            self.cohort.to_rfc3339(),
+            self.registered.to_string(),
+            self.verified.to_string(),
+            self.listed.to_string(),
+            self.transacted.to_string(),
+            optional_hours(self.median_hours_to_verification),
+            optional_hours(self.median_hours_to_first_listing),
+            optional_hours(self.median_hours_to_first_transaction),
+        ]
+    }
+}
+
+fn optional_hours(hours: Option<f64>) -> String {
+    hours.map(|hours| format!("{:.1}", hours)).unwrap_or_default()
+}
+
+/// Stage counts over the whole range and each stage's share of registrations
+#[derive(Debug, Clone, Serialize, PartialEq, ToSchema)]
+pub struct FunnelConversion {
+    pub registered: i64,
+    pub verified: i64,
+    pub listed: i64,
+    pub transacted: i64,
+    /// verified / registered
+    pub verification_rate: Option<f64>,
+    /// listed / registered
+    pub listing_rate: Option<f64>,
+    /// transacted / registered
+    pub transaction_rate: Option<f64>,
+}
+
+impl FunnelConversion {
+    pub fn from_cohorts(cohorts: &[FunnelCohort]) -> Self {
+        let registered: i64 = cohorts.iter().map(|c| c.registered).sum();
+        let verified: i64 = cohorts.iter().map(|c| c.verified).sum();
+        let listed: i64 = cohorts.iter().map(|c| c.listed).sum();
+        let transacted: i64 = cohorts.iter().map(|c| c.transacted).sum();
+        let rate = |count: i64| (registered > 0).then(|| count as f64 / registered as f64);
+
+        Self {
+            registered,
+            verified,
+            listed,
+            transacted,
+            verification_rate: rate(verified),
+            listing_rate: rate(listed),
+            transaction_rate: rate(transacted),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct FunnelReport {
+    pub interval: AnalyticsInterval,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub totals: FunnelConversion,
+    pub cohorts: Vec<FunnelCohort>,
+}
+
+/// Distinct sellers (listed or sold) and buyers (inquired or bought) per bucket
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct ActiveUsersBucket {
+    pub bucket: DateTime<Utc>,
+    pub active_sellers: i64,
+    pub active_buyers: i64,
+}
+
+impl AnalyticsRow for ActiveUsersBucket {
+    const COLUMNS: &'static [&'static str] = &["bucket", "active_sellers", "active_buyers"];
+
+    fn csv_values(&self) -> Vec<String> {
+        vec![
+            self.bucket.to_rfc3339(),
+            self.active_sellers.to_string(),
+            self.active_buyers.to_string(),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ActiveUsersReport {
+    pub interval: AnalyticsInterval,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub buckets: Vec<ActiveUsersBucket>,
+}
+
+/// Completed transactions per bucket, buyer country and product category
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct GmvBucket {
+    pub bucket: DateTime<Utc>,
+    /// Buyer's country, `unknown` when not set
+    pub country: String,
+    /// Pharmaceutical category, `uncategorized` when not set
+    pub category: String,
+    pub transactions: i64,
+    pub gmv: Decimal,
+}
+
+impl AnalyticsRow for GmvBucket {
+    const COLUMNS: &'static [&'static str] = &["bucket", "country", "category", "transactions", "gmv"];
+
+    fn csv_values(&self) -> Vec<String> {
+        vec![
+            self.bucket.to_rfc3339(),
+            self.country.clone(),
+            self.category.clone(),
+            self.transactions.to_string(),
+            self.gmv.to_string(),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct GmvReport {
+    pub interval: AnalyticsInterval,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub total_gmv: Decimal,
+    pub total_transactions: i64,
+    pub buckets: Vec<GmvBucket>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_query_defaults_and_limits() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 1, 0, 0, 0).unwrap();
+
+        let range = AnalyticsQuery::default().resolve(now).unwrap();
+        assert_eq!(range.interval, AnalyticsInterval::Week);
+        assert_eq!(range.format, AnalyticsFormat::Json);
+        assert_eq!(range.to, now);
+        assert_eq!(range.from, now - Duration::weeks(12));
+
+        let csv = AnalyticsQuery { format: Some("CSV".to_string()), interval: Some("monthly".to_string()), ..Default::default() };
+        let range = csv.resolve(now).unwrap();
+        assert_eq!((range.interval, range.format), (AnalyticsInterval::Month, AnalyticsFormat::Csv));
+
+        let inverted = AnalyticsQuery { from: Some(now), to: Some(now - Duration::days(1)), ..Default::default() };
+        assert!(inverted.resolve(now).is_err());
+
+        let too_long = AnalyticsQuery { from: Some(now - Duration::days(500)), interval: Some("day".to_string()), ..Default::default() };
+        assert!(too_long.resolve(now).is_err());
+
+        assert!(AnalyticsQuery { interval: Some("hour".to_string()), ..Default::default() }.resolve(now).is_err());
+        assert!(AnalyticsQuery { format: Some("xlsx".to_string()), ..Default::default() }.resolve(now).is_err());
+    }
+
+    #[test]
+    fn test_funnel_totals() {
+        let cohort = |registered, verified, listed, transacted| FunnelCohort {
+            cohort: Utc::now(),
+            registered,
+            verified,
+            listed,
+            transacted,
+            median_hours_to_verification: None,
+            median_hours_to_first_listing: None,
+            median_hours_to_first_transaction: None,
+        };
+
+        let totals = FunnelConversion::from_cohorts(&[cohort(10, 8, 4, 2), cohort(10, 2, 0, 0)]);
+        assert_eq!((totals.registered, totals.verified, totals.listed, totals.transacted), (20, 10, 4, 2));
+        assert_eq!(totals.verification_rate, Some(0.5));
+        assert_eq!(totals.transaction_rate, Some(0.1));
+
+        assert_eq!(FunnelConversion::from_cohorts(&[]).verification_rate, None);
+    }
+
+    #[test]
+    fn test_csv_export() {
+        let bucket = Utc.with_ymd_and_hms(2026, 2, 2, 0, 0, 0).unwrap();
+        let rows = vec![GmvBucket {
+            bucket,
+            country: "DE".to_string(),
+            category: "Oncology, injectables".to_string(),
+            transactions: 3,
+            gmv: Decimal::new(125050, 2),
+        }];
+
+        let csv = String::from_utf8(encode_csv(&rows).unwrap()).unwrap();
+        assert_eq!(
+            csv,
+            "bucket,country,category,transactions,gmv\n2026-02-02T00:00:00+00:00,DE,\"Oncology, injectables\",3,1250.50\n"
+        );
+    }
+}
@@ -1,6 +1,7 @@
 /// Models for AI-powered inventory import system
 
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use sqlx::FromRow;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
@@ -39,6 +40,8 @@ pub struct AiImportSession {
     pub rows_imported: Option<i32>,
     pub rows_failed: Option<i32>,
     pub rows_flagged_for_review: Option<i32>,
+    pub rows_rejected: Option<i32>,
+    pub rows_rolled_back: Option<i32>,
 
     // OpenFDA validation
     pub ndc_validated_count: Option<i32>,
@@ -59,13 +62,20 @@ pub struct AiImportSession {
     pub mapping_approved_at: Option<DateTime<Utc>>,
     pub import_started_at: Option<DateTime<Utc>>,
     pub import_completed_at: Option<DateTime<Utc>>,
+    pub rolled_back_at: Option<DateTime<Utc>>,
 
     // Metadata
     pub import_source: Option<String>,
     pub metadata: Option<serde_json::Value>,
+
+    // Mapping memory
+    pub header_fingerprint: Option<String>,
+    pub supplier_name: Option<String>,
+    pub mapping_source: Option<String>,
+    pub mapping_memory_id: Option<Uuid>,
 }
 
-#[derive(Debug, Clone, Serialize, FromRow)]
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
 pub struct AiImportRowResult {
     pub id: Uuid,
     pub session_id: Uuid,
@@ -98,6 +108,7 @@ pub struct AiImportRowResult {
     pub created_at: DateTime<Utc>,
     pub processed_at: Option<DateTime<Utc>>,
     pub imported_at: Option<DateTime<Utc>>,
+    pub rolled_back_at: Option<DateTime<Utc>>,
 }
 
 // ============================================================================
@@ -113,7 +124,7 @@ pub struct StartImportRequest {
     pub import_source: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ImportSessionResponse {
     pub id: Uuid,
     pub status: ImportStatus,
@@ -125,6 +136,9 @@ pub struct ImportSessionResponse {
     pub suggested_mapping: Option<ColumnMapping>,
     pub confidence_scores: Option<serde_json::Value>,
     pub warnings: Vec<String>,
+    pub supplier_name: Option<String>,
+    /// "ai" for a fresh analysis, "memory" when a remembered mapping was reused
+    pub mapping_source: Option<String>,
 
     // Statistics
     pub total_rows: Option<u32>,
@@ -132,6 +146,8 @@ pub struct ImportSessionResponse {
     pub rows_imported: u32,
     pub rows_failed: u32,
     pub rows_flagged: u32,
+    pub rows_rejected: u32,
+    pub rows_rolled_back: u32,
 
     // Validation stats
     pub ndc_validated: u32,
@@ -147,18 +163,23 @@ pub struct ImportSessionResponse {
     // Timestamps
     pub created_at: DateTime<Utc>,
     pub completed_at: Option<DateTime<Utc>>,
+    pub rolled_back_at: Option<DateTime<Utc>>,
 
     // Error if failed
     pub error_message: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, ToSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ImportStatus {
     Analyzing,
     MappingReview,
+    /// Document extraction awaiting human review of the extracted fields
+    ExtractionReview,
     Importing,
     Completed,
+    /// Imported stock reversed after completion
+    RolledBack,
     Failed,
     Cancelled,
 }
@@ -168,15 +189,17 @@ impl std::fmt::Display for ImportStatus {
         match self {
             ImportStatus::Analyzing => write!(f, "analyzing"),
             ImportStatus::MappingReview => write!(f, "mapping_review"),
+            ImportStatus::ExtractionReview => write!(f, "extraction_review"),
             ImportStatus::Importing => write!(f, "importing"),
             ImportStatus::Completed => write!(f, "completed"),
+            ImportStatus::RolledBack => write!(f, "rolled_back"),
             ImportStatus::Failed => write!(f, "failed"),
             ImportStatus::Cancelled => write!(f, "cancelled"),
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct ColumnMapping {
     pub ndc_code: Option<String>,
     pub brand_name: Option<String>,
@@ -209,9 +232,28 @@ impl ColumnMapping {
             dosage_form: None,
         }
     }
+
+    /// Every field mapped to a column of its own name (rows built from extracted documents)
+    pub fn identity() -> Self {
+        let column = |name: &str| Some(name.to_string());
+        Self {
+            ndc_code: column("ndc_code"),
+            brand_name: column("brand_name"),
+            generic_name: column("generic_name"),
+            manufacturer: column("manufacturer"),
+            quantity: column("quantity"),
+            batch_number: column("batch_number"),
+            expiry_date: column("expiry_date"),
+            unit_price: column("unit_price"),
+            storage_location: column("storage_location"),
+            category: column("category"),
+            strength: column("strength"),
+            dosage_form: column("dosage_form"),
+        }
+    }
 }
 
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize, ToSchema)]
 pub struct MappedInventoryRow {
     pub row_number: usize,
     pub ndc_code: Option<String>,
@@ -235,8 +277,10 @@ impl ImportStatus {
         match s {
             "analyzing" => ImportStatus::Analyzing,
             "mapping_review" => ImportStatus::MappingReview,
+            "extraction_review" => ImportStatus::ExtractionReview,
             "importing" => ImportStatus::Importing,
             "completed" => ImportStatus::Completed,
+            "rolled_back" => ImportStatus::RolledBack,
             "failed" => ImportStatus::Failed,
             "cancelled" => ImportStatus::Cancelled,
             _ => ImportStatus::Analyzing,
@@ -276,11 +320,15 @@ impl From<AiImportSession> for ImportSessionResponse {
             suggested_mapping,
             confidence_scores: session.ai_confidence_scores,
             warnings,
+            supplier_name: session.supplier_name,
+            mapping_source: session.mapping_source,
             total_rows: session.total_rows.map(|r| r as u32),
             rows_processed: rows_processed as u32,
             rows_imported: session.rows_imported.unwrap_or(0) as u32,
             rows_failed: session.rows_failed.unwrap_or(0) as u32,
             rows_flagged: session.rows_flagged_for_review.unwrap_or(0) as u32,
+            rows_rejected: session.rows_rejected.unwrap_or(0) as u32,
+            rows_rolled_back: session.rows_rolled_back.unwrap_or(0) as u32,
             ndc_validated: session.ndc_validated_count.unwrap_or(0) as u32,
             ndc_not_found: session.ndc_not_found_count.unwrap_or(0) as u32,
             auto_enriched: session.auto_enriched_count.unwrap_or(0) as u32,
@@ -288,7 +336,567 @@ impl From<AiImportSession> for ImportSessionResponse {
             progress_percentage: progress.min(100),
             created_at: session.created_at,
             completed_at: session.import_completed_at,
+            rolled_back_at: session.rolled_back_at,
             error_message: session.error_message,
         }
     }
 }
+
+// ============================================================================
+// Column-Mapping Memory
+// ============================================================================
+
+/// A confirmed column mapping, reused for later files with the same header row
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct AiImportMappingMemory {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub header_fingerprint: String,
+    pub headers: serde_json::Value,
+    pub supplier_name: Option<String>,
+    pub mapping: serde_json::Value,
+    pub confidence_scores: Option<serde_json::Value>,
+    pub times_reused: i32,
+    pub last_session_id: Option<Uuid>,
+    pub confirmed_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// SHA256 over the trimmed, sorted header names. Mappings refer to columns by name,
+/// so reordered columns keep the fingerprint while added or renamed ones change it.
+pub fn header_fingerprint(headers: &[String]) -> String {
+    use sha2::{Digest, Sha256};
+
+    let mut names: Vec<&str> = headers.iter()
+        .map(|h| h.trim())
+        .filter(|h| !h.is_empty())
+        .collect();
+    names.sort_unstable();
+    names.dedup();
+
+    let mut hasher = Sha256::new();
+    for name in names {
+        hasher.update(name.as_bytes());
+        hasher.update([0x1f]);
+    }
+    hex::encode(hasher.finalize())
+}
+
+impl ColumnMapping {
+    /// Source columns the mapping reads from
+    pub fn mapped_columns(&self) -> Vec<&str> {
+        [
+            &self.ndc_code, &self.brand_name, &self.generic_name, &self.manufacturer,
+            &self.quantity, &self.batch_number, &self.expiry_date, &self.unit_price,
+            &self.storage_location, &self.category, &self.strength, &self.dosage_form,
+        ]
+        .into_iter()
+        .filter_map(|column| column.as_deref())
+        .collect()
+    }
+
+    /// Whether every mapped column is present in `headers`
+    pub fn fits_headers(&self, headers: &[String]) -> bool {
+        self.mapped_columns().iter().all(|column| headers.iter().any(|h| h == column))
+    }
+}
+
+// ============================================================================
+// Row Review and Rollback
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RowDecision {
+    Accept,
+    Reject,
+}
+
+impl RowDecision {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RowDecision::Accept => "accept",
+            RowDecision::Reject => "reject",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Validate, ToSchema)]
+pub struct RowDecisionInput {
+    #[validate(range(min = 1))]
+    pub row_number: i32,
+    pub decision: RowDecision,
+    #[validate(length(max = 1000))]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct UpdateRowDecisionsRequest {
+    #[validate(length(min = 1, max = 5000), nested)]
+    pub decisions: Vec<RowDecisionInput>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct AiImportRowDecision {
+    pub session_id: Uuid,
+    pub row_number: i32,
+    pub decision: String,
+    pub reason: Option<String>,
+    pub decided_by: Uuid,
+    pub decided_at: DateTime<Utc>,
+}
+
+/// A file row as it will be imported with the session's mapping, for review before import
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RowPreview {
+    pub row_number: usize,
+    pub mapped: MappedInventoryRow,
+    /// "reject" rows are skipped on import; rows without a decision are imported
+    pub decision: Option<String>,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RowPreviewResponse {
+    pub session_id: Uuid,
+    pub total_rows: usize,
+    pub rows_rejected: usize,
+    pub rows: Vec<RowPreview>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RollbackSkippedRow {
+    pub row_number: i32,
+    pub inventory_id: Uuid,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RollbackResult {
+    pub session_id: Uuid,
+    pub rows_rolled_back: u32,
+    pub quantity_reversed: i64,
+    /// Rows left in place because the stock changed after import; a later rollback retries them
+    pub skipped: Vec<RollbackSkippedRow>,
+    pub status: ImportStatus,
+}
+
+/// Why an imported inventory row can't be reversed automatically, if it can't
+pub fn rollback_blocker(imported_quantity: i32, current_quantity: i32, has_inquiries: bool) -> Option<String> {
+    if has_inquiries {
+        Some("Stock has marketplace inquiries".to_string())
+    } else if current_quantity != imported_quantity {
+        Some(format!(
+            "Quantity changed since import ({} imported, {} now)",
+            imported_quantity, current_quantity
+        ))
+    } else {
+        None
+    }
+}
+
+// ============================================================================
+// Document Extraction (PDFs and images)
+// ============================================================================
+
+/// Inventory fields read from document line items, in `ColumnMapping` order
+pub const DOCUMENT_LINE_FIELDS: [&str; 12] = [
+    "ndc_code", "brand_name", "generic_name", "manufacturer", "quantity", "batch_number",
+    "expiry_date", "unit_price", "storage_location", "category", "strength", "dosage_form",
+];
+
+/// Header fields of a Certificate of Analysis beyond the inventory fields
+pub const COA_FIELDS: [&str; 7] = [
+    "product_name", "manufacture_date", "test_date", "assay_result", "specification",
+    "conclusion", "approved_by",
+];
+
+/// Fields extracted below this confidence must be confirmed or corrected before applying
+pub const REVIEW_CONFIDENCE_THRESHOLD: f64 = 0.85;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DocumentKind {
+    #[serde(rename = "coa")]
+    CertificateOfAnalysis,
+    PriceList,
+    PackingList,
+}
+
+impl std::fmt::Display for DocumentKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DocumentKind::CertificateOfAnalysis => write!(f, "coa"),
+            DocumentKind::PriceList => write!(f, "price_list"),
+            DocumentKind::PackingList => write!(f, "packing_list"),
+        }
+    }
+}
+
+impl DocumentKind {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "coa" | "certificate_of_analysis" => Some(DocumentKind::CertificateOfAnalysis),
+            "price_list" => Some(DocumentKind::PriceList),
+            "packing_list" => Some(DocumentKind::PackingList),
+            _ => None,
+        }
+    }
+}
+
+/// A single extracted value with the model's confidence in it (1.0 once a reviewer confirms it)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, ToSchema)]
+pub struct ExtractedField {
+    pub value: Option<String>,
+    pub confidence: f64,
+    #[serde(default)]
+    pub reviewed: bool,
+}
+
+pub type ExtractedFields = std::collections::BTreeMap<String, ExtractedField>;
+
+/// Structured content of a COA, price list or packing list. Header fields apply to the
+/// whole document (supplier, lot on a COA, shipment date); line items are one product each.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentExtraction {
+    pub document_kind: DocumentKind,
+    #[serde(default)]
+    pub header: ExtractedFields,
+    #[serde(default)]
+    pub line_items: Vec<ExtractedFields>,
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+/// A field the reviewer still has to look at: `line` is `None` for header fields
+#[derive(Debug, Clone, Serialize, PartialEq, ToSchema)]
+pub struct FieldReview {
+    pub line: Option<usize>,
+    pub field: String,
+    pub value: Option<String>,
+    pub confidence: f64,
+}
+
+/// A reviewer's confirmation or correction of one field
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct FieldCorrection {
+    pub line: Option<usize>,
+    pub field: String,
+    pub value: Option<String>,
+}
+
+impl DocumentExtraction {
+    /// Parse Claude's extraction JSON, tolerating text around the object, and normalize it
+    pub fn from_ai_response(content: &str) -> std::result::Result<Self, String> {
+        let start = content.find('{').ok_or("AI response missing JSON object")?;
+        let end = content.rfind('}').ok_or("AI response missing JSON closing brace")?;
+        if end < start {
+            return Err("AI response missing JSON object".to_string());
+        }
+
+        let extraction: DocumentExtraction = serde_json::from_str(&content[start..=end])
+            .map_err(|e| format!("Failed to parse AI extraction: {}", e))?;
+
+        Ok(extraction.normalized())
+    }
+
+    /// Clamp confidences to 0..1, turn blank values into `None` and drop empty line items
+    pub fn normalized(mut self) -> Self {
+        for fields in std::iter::once(&mut self.header).chain(self.line_items.iter_mut()) {
+            for field in fields.values_mut() {
+                field.value = field.value.take()
+                    .map(|v| v.trim().to_string())
+                    .filter(|v| !v.is_empty());
+                field.confidence = if field.confidence.is_finite() { field.confidence.clamp(0.0, 1.0) } else { 0.0 };
+                field.reviewed = false;
+            }
+        }
+        self.line_items.retain(|item| item.values().any(|f| f.value.is_some()));
+
+        self
+    }
+
+    /// Unreviewed fields with a value below `REVIEW_CONFIDENCE_THRESHOLD`
+    pub fn fields_for_review(&self) -> Vec<FieldReview> {
+        let header = self.header.iter().map(|(name, field)| (None, name, field));
+        let lines = self.line_items.iter().enumerate()
+            .flat_map(|(idx, item)| item.iter().map(move |(name, field)| (Some(idx), name, field)));
+
+        header
+            .chain(lines)
+            .filter(|(_, _, field)| {
+                !field.reviewed && field.value.is_some() && field.confidence < REVIEW_CONFIDENCE_THRESHOLD
+            })
+            .map(|(line, name, field)| FieldReview {
+                line,
+                field: name.clone(),
+                value: field.value.clone(),
+                confidence: field.confidence,
+            })
+            .collect()
+    }
+
+    /// Apply reviewer corrections; a corrected or confirmed field is fully trusted
+    pub fn apply_corrections(&mut self, corrections: &[FieldCorrection]) -> std::result::Result<(), String> {
+        for correction in corrections {
+            let fields = match correction.line {
+                None => &mut self.header,
+                Some(idx) => self.line_items.get_mut(idx)
+                    .ok_or_else(|| format!("Line item {} does not exist", idx))?,
+            };
+
+            fields.insert(correction.field.clone(), ExtractedField {
+                value: correction.value.as_ref().map(|v| v.trim().to_string()).filter(|v| !v.is_empty()),
+                confidence: 1.0,
+                reviewed: true,
+            });
+        }
+        Ok(())
+    }
+
+    /// Rows for the batch importer, one per line item, in `DOCUMENT_LINE_FIELDS` columns. A
+    /// line falls back to the header for fields it lacks (a COA is a single header-only row).
+    pub fn inventory_rows(&self) -> (Vec<String>, Vec<Vec<String>>) {
+        let headers = DOCUMENT_LINE_FIELDS.iter().map(|f| f.to_string()).collect();
+
+        let header_row = |field: &str| -> Option<String> {
+            let value = self.header.get(field).and_then(|f| f.value.clone());
+            // A COA names its product once, in the header
+            if value.is_none() && field == "brand_name" {
+                return self.header.get("product_name").and_then(|f| f.value.clone());
+            }
+            value
+        };
+
+        let build_row = |item: Option<&ExtractedFields>| -> Vec<String> {
+            DOCUMENT_LINE_FIELDS
+                .iter()
+                .map(|field| {
+                    item.and_then(|i| i.get(*field))
+                        .and_then(|f| f.value.clone())
+                        .or_else(|| header_row(field))
+                        .unwrap_or_default()
+                })
+                .collect()
+        };
+
+        let rows = if self.line_items.is_empty() {
+            vec![build_row(None)]
+        } else {
+            self.line_items.iter().map(|item| build_row(Some(item))).collect()
+        };
+
+        (headers, rows)
+    }
+
+    /// A header field's value, e.g. the lot number a COA certifies
+    pub fn header_value(&self, field: &str) -> Option<&str> {
+        self.header.get(field).and_then(|f| f.value.as_deref())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct AiDocumentExtraction {
+    pub id: Uuid,
+    pub session_id: Uuid,
+    pub user_id: Uuid,
+    pub document_kind: String,
+    pub media_type: String,
+    pub extraction: serde_json::Value,
+    pub status: String,
+    pub target: Option<String>,
+    pub transaction_id: Option<Uuid>,
+    pub linked_inventory_ids: Vec<Uuid>,
+    pub reviewed_by: Option<Uuid>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub applied_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DocumentExtractionResponse {
+    pub session_id: Uuid,
+    pub document_kind: DocumentKind,
+    pub media_type: String,
+    pub status: String,
+    #[schema(value_type = std::collections::BTreeMap<String, ExtractedField>)]
+    pub header: ExtractedFields,
+    #[schema(value_type = Vec<std::collections::BTreeMap<String, ExtractedField>>)]
+    pub line_items: Vec<ExtractedFields>,
+    pub warnings: Vec<String>,
+    pub review_threshold: f64,
+    pub fields_for_review: Vec<FieldReview>,
+    pub target: Option<String>,
+    pub transaction_id: Option<Uuid>,
+    pub linked_inventory_ids: Vec<Uuid>,
+    pub applied_at: Option<DateTime<Utc>>,
+}
+
+impl DocumentExtractionResponse {
+    pub fn new(record: &AiDocumentExtraction, extraction: DocumentExtraction) -> Self {
+        Self {
+            session_id: record.session_id,
+            fields_for_review: extraction.fields_for_review(),
+            document_kind: extraction.document_kind,
+            media_type: record.media_type.clone(),
+            status: record.status.clone(),
+            header: extraction.header,
+            line_items: extraction.line_items,
+            warnings: extraction.warnings,
+            review_threshold: REVIEW_CONFIDENCE_THRESHOLD,
+            target: record.target.clone(),
+            transaction_id: record.transaction_id,
+            linked_inventory_ids: record.linked_inventory_ids.clone(),
+            applied_at: record.applied_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ReviewExtractionRequest {
+    /// Correct the document type Claude detected
+    pub document_kind: Option<String>,
+    #[serde(default)]
+    pub corrections: Vec<FieldCorrection>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExtractionTarget {
+    /// Price and packing lists become inventory; a COA is linked to the lots it certifies
+    Inventory,
+    /// Attached to a marketplace transaction as a transaction document
+    Transaction,
+}
+
+impl std::fmt::Display for ExtractionTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExtractionTarget::Inventory => write!(f, "inventory"),
+            ExtractionTarget::Transaction => write!(f, "transaction"),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ApplyExtractionRequest {
+    pub target: ExtractionTarget,
+    pub transaction_id: Option<Uuid>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AI_RESPONSE: &str = r#"Here is the extraction:
+{
+  "document_kind": "packing_list",
+  "header": {
+    "manufacturer": {"value": "Acme Pharma", "confidence": 0.97},
+    "expiry_date": {"value": "2027-03-31", "confidence": 0.6}
+  },
+  "line_items": [
+    {"ndc_code": {"value": "12345-678-90", "confidence": 0.99}, "quantity": {"value": " 120 ", "confidence": 1.4}},
+    {"ndc_code": {"value": "", "confidence": 0.2}},
+    {"ndc_code": {"value": "98765-432-10", "confidence": 0.7}, "expiry_date": {"value": "2026-01-31", "confidence": 0.9}}
+  ],
+  "warnings": ["Page 2 is partially illegible"]
+}"#;
+
+    #[test]
+    fn test_rollback_blocker() {
+        assert_eq!(rollback_blocker(120, 120, false), None);
+        assert!(rollback_blocker(120, 80, false).unwrap().contains("120 imported, 80 now"));
+        assert!(rollback_blocker(120, 120, true).unwrap().contains("inquiries"));
+    }
+
+    #[test]
+    fn test_header_fingerprint_ignores_order_and_padding() {
+        let headers = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        let weekly = header_fingerprint(&headers(&["NDC", "Product", "Qty", "Price"]));
+
+        assert_eq!(weekly, header_fingerprint(&headers(&["Price", " NDC", "Qty", "Product "])));
+        assert_ne!(weekly, header_fingerprint(&headers(&["NDC", "Product", "Qty", "Unit Price"])));
+        assert_ne!(weekly, header_fingerprint(&headers(&["NDC", "Product", "Qty", "Price", "Lot"])));
+        assert_eq!(weekly.len(), 64);
+    }
+
+    #[test]
+    fn test_mapping_fits_headers() {
+        let headers: Vec<String> = ["NDC", "Qty", "Expiry"].iter().map(|h| h.to_string()).collect();
+        let mut mapping = ColumnMapping::new();
+        mapping.ndc_code = Some("NDC".to_string());
+        mapping.quantity = Some("Qty".to_string());
+
+        assert_eq!(mapping.mapped_columns(), vec!["NDC", "Qty"]);
+        assert!(mapping.fits_headers(&headers));
+        mapping.expiry_date = Some("Expiry Date".to_string());
+        assert!(!mapping.fits_headers(&headers));
+    }
+
+    #[test]
+    fn test_parse_extraction() {
+        let extraction = DocumentExtraction::from_ai_response(AI_RESPONSE).unwrap();
+
+        assert_eq!(extraction.document_kind, DocumentKind::PackingList);
+        // The blank line is dropped
+        assert_eq!(extraction.line_items.len(), 2);
+        let quantity = &extraction.line_items[0]["quantity"];
+        assert_eq!(quantity.value.as_deref(), Some("120"));
+        assert_eq!(quantity.confidence, 1.0);
+        assert_eq!(extraction.warnings.len(), 1);
+        assert!(DocumentExtraction::from_ai_response("no json here").is_err());
+    }
+
+    #[test]
+    fn test_review_and_corrections() {
+        let mut extraction = DocumentExtraction::from_ai_response(AI_RESPONSE).unwrap();
+
+        let review = extraction.fields_for_review();
+        assert_eq!(review.len(), 2);
+        assert_eq!((review[0].line, review[0].field.as_str()), (None, "expiry_date"));
+        assert_eq!((review[1].line, review[1].field.as_str()), (Some(1), "ndc_code"));
+
+        extraction.apply_corrections(&[
+            FieldCorrection { line: None, field: "expiry_date".into(), value: Some("2027-03-31".into()) },
+            FieldCorrection { line: Some(1), field: "ndc_code".into(), value: Some("98765-0432-10".into()) },
+        ]).unwrap();
+        assert!(extraction.fields_for_review().is_empty());
+        assert!(extraction.apply_corrections(&[
+            FieldCorrection { line: Some(7), field: "quantity".into(), value: None },
+        ]).is_err());
+    }
+
+    #[test]
+    fn test_inventory_rows_fall_back_to_header() {
+        let extraction = DocumentExtraction::from_ai_response(AI_RESPONSE).unwrap();
+        let (headers, rows) = extraction.inventory_rows();
+        let column = |name: &str| headers.iter().position(|h| h == name).unwrap();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0][column("manufacturer")], "Acme Pharma");
+        assert_eq!(rows[0][column("expiry_date")], "2027-03-31");
+        assert_eq!(rows[1][column("expiry_date")], "2026-01-31");
+        assert_eq!(rows[1][column("quantity")], "");
+    }
+
+    #[test]
+    fn test_coa_is_a_single_row() {
+        let coa = DocumentExtraction::from_ai_response(r#"{
+            "document_kind": "coa",
+            "header": {
+                "product_name": {"value": "Amoxicillin 500mg Capsules", "confidence": 0.95},
+                "batch_number": {"value": "LOT-2291", "confidence": 0.92},
+                "conclusion": {"value": "Complies", "confidence": 0.9}
+            }
+        }"#).unwrap();
+        let (headers, rows) = coa.inventory_rows();
+
+        assert_eq!(coa.document_kind, DocumentKind::CertificateOfAnalysis);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0][headers.iter().position(|h| h == "brand_name").unwrap()], "Amoxicillin 500mg Capsules");
+        assert_eq!(coa.header_value("batch_number"), Some("LOT-2291"));
+    }
+}
@@ -0,0 +1,306 @@
+/// AI quota tiers, admin overrides and top-ups
+
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+// ============================================================================
+// Quota Features
+// ============================================================================
+
+/// A metered AI feature and its `user_ai_usage_limits` columns
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaFeature {
+    Imports,
+    NlQueries,
+    InquiryAssists,
+    PricingSuggestions,
+    ErpAiMapping,
+    ErpAiAnalysis,
+    ErpAiConflict,
+    /// Monthly Anthropic spend shared by all features, in USD
+    AiCostUsd,
+}
+
+impl QuotaFeature {
+    pub const ALL: [QuotaFeature; 8] = [
+        QuotaFeature::Imports,
+        QuotaFeature::NlQueries,
+        QuotaFeature::InquiryAssists,
+        QuotaFeature::PricingSuggestions,
+        QuotaFeature::ErpAiMapping,
+        QuotaFeature::ErpAiAnalysis,
+        QuotaFeature::ErpAiConflict,
+        QuotaFeature::AiCostUsd,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QuotaFeature::Imports => "imports",
+            QuotaFeature::NlQueries => "nl_queries",
+            QuotaFeature::InquiryAssists => "inquiry_assists",
+            QuotaFeature::PricingSuggestions => "pricing_suggestions",
+            QuotaFeature::ErpAiMapping => "erp_ai_mapping",
+            QuotaFeature::ErpAiAnalysis => "erp_ai_analysis",
+            QuotaFeature::ErpAiConflict => "erp_ai_conflict",
+            QuotaFeature::AiCostUsd => "ai_cost_usd",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|f| f.as_str() == s)
+    }
+
+    /// Limit column, shared by `user_ai_usage_limits` and `ai_quota_tiers`
+    pub fn limit_column(&self) -> &'static str {
+        match self {
+            QuotaFeature::Imports => "monthly_import_limit",
+            QuotaFeature::NlQueries => "monthly_nl_query_limit",
+            QuotaFeature::InquiryAssists => "monthly_inquiry_assist_limit",
+            QuotaFeature::PricingSuggestions => "monthly_pricing_suggestion_limit",
+            QuotaFeature::ErpAiMapping => "monthly_erp_ai_mapping_limit",
+            QuotaFeature::ErpAiAnalysis => "monthly_erp_ai_analysis_limit",
+            QuotaFeature::ErpAiConflict => "monthly_erp_ai_conflict_limit",
+            QuotaFeature::AiCostUsd => "monthly_ai_cost_limit_usd",
+        }
+    }
+
+    pub fn used_column(&self) -> &'static str {
+        match self {
+            QuotaFeature::Imports => "monthly_imports_used",
+            QuotaFeature::NlQueries => "monthly_nl_queries_used",
+            QuotaFeature::InquiryAssists => "monthly_inquiry_assists_used",
+            QuotaFeature::PricingSuggestions => "monthly_pricing_suggestions_used",
+            QuotaFeature::ErpAiMapping => "monthly_erp_ai_mapping_used",
+            QuotaFeature::ErpAiAnalysis => "monthly_erp_ai_analysis_used",
+            QuotaFeature::ErpAiConflict => "monthly_erp_ai_conflict_used",
+            QuotaFeature::AiCostUsd => "monthly_ai_cost_used_usd",
+        }
+    }
+
+    pub fn is_cost(&self) -> bool {
+        matches!(self, QuotaFeature::AiCostUsd)
+    }
+
+    /// Top-up price per unit (per request, or per dollar of AI spend)
+    pub fn topup_unit_price_usd(&self) -> Decimal {
+        match self {
+            QuotaFeature::Imports => Decimal::new(50, 2),
+            QuotaFeature::NlQueries => Decimal::new(5, 2),
+            QuotaFeature::InquiryAssists => Decimal::new(2, 2),
+            QuotaFeature::PricingSuggestions => Decimal::new(5, 2),
+            QuotaFeature::ErpAiMapping => Decimal::new(200, 2),
+            QuotaFeature::ErpAiAnalysis => Decimal::new(10, 2),
+            QuotaFeature::ErpAiConflict => Decimal::new(25, 2),
+            QuotaFeature::AiCostUsd => Decimal::ONE,
+        }
+    }
+}
+
+impl std::fmt::Display for QuotaFeature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+// ============================================================================
+// Database Models
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct AiQuotaTier {
+    pub tier: String,
+    pub display_name: String,
+    pub monthly_price_usd: Decimal,
+    pub monthly_import_limit: i32,
+    pub monthly_nl_query_limit: i32,
+    pub monthly_inquiry_assist_limit: i32,
+    pub monthly_pricing_suggestion_limit: i32,
+    pub monthly_erp_ai_mapping_limit: i32,
+    pub monthly_erp_ai_analysis_limit: i32,
+    pub monthly_erp_ai_conflict_limit: i32,
+    pub monthly_ai_cost_limit_usd: Decimal,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl AiQuotaTier {
+    pub fn limit(&self, feature: QuotaFeature) -> Decimal {
+        match feature {
+            QuotaFeature::Imports => self.monthly_import_limit.into(),
+            QuotaFeature::NlQueries => self.monthly_nl_query_limit.into(),
+            QuotaFeature::InquiryAssists => self.monthly_inquiry_assist_limit.into(),
+            QuotaFeature::PricingSuggestions => self.monthly_pricing_suggestion_limit.into(),
+            QuotaFeature::ErpAiMapping => self.monthly_erp_ai_mapping_limit.into(),
+            QuotaFeature::ErpAiAnalysis => self.monthly_erp_ai_analysis_limit.into(),
+            QuotaFeature::ErpAiConflict => self.monthly_erp_ai_conflict_limit.into(),
+            QuotaFeature::AiCostUsd => self.monthly_ai_cost_limit_usd,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct AiQuotaOverride {
+    pub user_id: Uuid,
+    pub feature: String,
+    pub monthly_limit: Decimal,
+    pub reason: Option<String>,
+    pub set_by: Option<Uuid>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct AiQuotaTopup {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub feature: String,
+    pub amount: Decimal,
+    pub price_usd: Decimal,
+    pub status: String,
+    pub payment_reference: Option<String>,
+    pub period_end: NaiveDate,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A user's limits row; the ERP columns predate NOT NULL defaults
+#[derive(Debug, Clone, FromRow)]
+pub struct UserAiUsageRow {
+    pub plan_tier: String,
+    pub limit_period_start: NaiveDate,
+    pub limit_period_end: NaiveDate,
+    pub monthly_import_limit: i32,
+    pub monthly_imports_used: i32,
+    pub monthly_nl_query_limit: i32,
+    pub monthly_nl_queries_used: i32,
+    pub monthly_inquiry_assist_limit: i32,
+    pub monthly_inquiry_assists_used: i32,
+    pub monthly_pricing_suggestion_limit: i32,
+    pub monthly_pricing_suggestions_used: i32,
+    pub monthly_erp_ai_mapping_limit: Option<i32>,
+    pub monthly_erp_ai_mapping_used: Option<i32>,
+    pub monthly_erp_ai_analysis_limit: Option<i32>,
+    pub monthly_erp_ai_analysis_used: Option<i32>,
+    pub monthly_erp_ai_conflict_limit: Option<i32>,
+    pub monthly_erp_ai_conflict_used: Option<i32>,
+    pub monthly_ai_cost_limit_usd: Decimal,
+    pub monthly_ai_cost_used_usd: Decimal,
+}
+
+impl UserAiUsageRow {
+    /// (limit, used) of a feature
+    pub fn usage(&self, feature: QuotaFeature) -> (Decimal, Decimal) {
+        let counts = |limit: i32, used: i32| (Decimal::from(limit), Decimal::from(used));
+        match feature {
+            QuotaFeature::Imports => counts(self.monthly_import_limit, self.monthly_imports_used),
+            QuotaFeature::NlQueries => counts(self.monthly_nl_query_limit, self.monthly_nl_queries_used),
+            QuotaFeature::InquiryAssists => counts(self.monthly_inquiry_assist_limit, self.monthly_inquiry_assists_used),
+            QuotaFeature::PricingSuggestions => {
+                counts(self.monthly_pricing_suggestion_limit, self.monthly_pricing_suggestions_used)
+            }
+            QuotaFeature::ErpAiMapping => counts(
+                self.monthly_erp_ai_mapping_limit.unwrap_or(0),
+                self.monthly_erp_ai_mapping_used.unwrap_or(0),
+            ),
+            QuotaFeature::ErpAiAnalysis => counts(
+                self.monthly_erp_ai_analysis_limit.unwrap_or(0),
+                self.monthly_erp_ai_analysis_used.unwrap_or(0),
+            ),
+            QuotaFeature::ErpAiConflict => counts(
+                self.monthly_erp_ai_conflict_limit.unwrap_or(0),
+                self.monthly_erp_ai_conflict_used.unwrap_or(0),
+            ),
+            QuotaFeature::AiCostUsd => (self.monthly_ai_cost_limit_usd, self.monthly_ai_cost_used_usd),
+        }
+    }
+}
+
+/// Limit of a feature: an override replaces the tier limit, top-ups add to either
+pub fn effective_limit(tier_limit: Decimal, override_limit: Option<Decimal>, topups: Decimal) -> Decimal {
+    override_limit.unwrap_or(tier_limit) + topups
+}
+
+// ============================================================================
+// API Request/Response Models
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct FeatureUsage {
+    pub feature: QuotaFeature,
+    pub limit: f64,
+    pub used: f64,
+    pub remaining: f64,
+    pub tier_limit: f64,
+    pub override_limit: Option<f64>,
+    pub topup: f64,
+}
+
+/// Per-feature usage included in every `GET /api/*/quota` response
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AiQuotaUsage {
+    pub plan_tier: String,
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+    pub features: Vec<FeatureUsage>,
+}
+
+impl AiQuotaUsage {
+    pub fn feature(&self, feature: QuotaFeature) -> Option<&FeatureUsage> {
+        self.features.iter().find(|f| f.feature == feature)
+    }
+}
+
+pub(crate) fn to_f64(value: Decimal) -> f64 {
+    value.to_f64().unwrap_or(0.0)
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PurchaseTopupRequest {
+    pub feature: QuotaFeature,
+    /// Requests, or dollars of AI spend for `ai_cost_usd`
+    pub amount: Decimal,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PurchaseTopupResponse {
+    pub topup: AiQuotaTopup,
+    pub usage: AiQuotaUsage,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SetPlanTierRequest {
+    pub tier: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SetQuotaOverrideRequest {
+    pub monthly_limit: Decimal,
+    pub reason: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_effective_limit() {
+        assert_eq!(effective_limit(dec!(100), None, dec!(0)), dec!(100));
+        assert_eq!(effective_limit(dec!(100), Some(dec!(20)), dec!(0)), dec!(20));
+        assert_eq!(effective_limit(dec!(100), Some(dec!(20)), dec!(50)), dec!(70));
+    }
+
+    #[test]
+    fn test_feature_names_round_trip() {
+        for feature in QuotaFeature::ALL {
+            assert_eq!(QuotaFeature::from_str(feature.as_str()), Some(feature));
+            assert_eq!(serde_json::to_value(feature).unwrap(), feature.as_str());
+        }
+        assert_eq!(QuotaFeature::from_str("tokens"), None);
+    }
+}
@@ -0,0 +1,446 @@
+/// Alert chat channel models (Slack / Microsoft Teams)
+///
+/// Channels are connected with an incoming webhook URL or, for Slack, an OAuth app
+/// install. Alert preferences route alert types to channels; alerts are posted as Slack
+/// Block Kit messages or Teams Adaptive Cards laid out per alert type.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use utoipa::ToSchema;
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+use super::alerts::AlertNotification;
+
+// ============================================================================
+// ENUMS
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ChatProvider {
+    Slack,
+    Teams,
+}
+
+impl ChatProvider {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChatProvider::Slack => "slack",
+            ChatProvider::Teams => "teams",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "slack" => Some(ChatProvider::Slack),
+            "teams" => Some(ChatProvider::Teams),
+            _ => None,
+        }
+    }
+
+    /// Whether `host` serves this provider's incoming webhooks: Slack app webhooks, Teams
+    /// Office 365 connectors and Teams Workflows (Power Automate) webhook triggers
+    pub fn is_webhook_host(&self, host: &str) -> bool {
+        let host = host.to_lowercase();
+        match self {
+            ChatProvider::Slack => host == "hooks.slack.com",
+            ChatProvider::Teams => {
+                host.ends_with(".webhook.office.com")
+                    || host.ends_with(".logic.azure.com")
+                    || host.ends_with(".powerplatform.com")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelConnectionType {
+    IncomingWebhook,
+    Oauth,
+}
+
+impl ChannelConnectionType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChannelConnectionType::IncomingWebhook => "incoming_webhook",
+            ChannelConnectionType::Oauth => "oauth",
+        }
+    }
+}
+
+// ============================================================================
+// CHANNELS
+// ============================================================================
+
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct AlertChannel {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub provider: String,
+    pub connection_type: String,
+    pub name: String,
+    pub status: String,
+    #[serde(skip_serializing)]
+    pub webhook_url: Option<String>,     // Encrypted
+    pub channel_name: Option<String>,
+    pub workspace_name: Option<String>,
+    #[serde(skip_serializing)]
+    pub client_id: Option<String>,       // Encrypted
+    #[serde(skip_serializing)]
+    pub client_secret: Option<String>,   // Encrypted
+    #[serde(skip_serializing)]
+    pub access_token: Option<String>,    // Encrypted
+    pub last_error: Option<String>,
+    pub last_delivery_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct CreateAlertChannelRequest {
+    pub provider: ChatProvider,
+    pub connection_type: ChannelConnectionType,
+    #[validate(length(min = 1, max = 255))]
+    pub name: String,
+    /// Incoming webhook URL (connection_type = incoming_webhook)
+    #[validate(length(min = 1, max = 2048))]
+    pub webhook_url: Option<String>,
+    /// Display name of the channel the webhook posts to, e.g. "#trading-desk"
+    #[validate(length(min = 1, max = 255))]
+    pub channel_name: Option<String>,
+    /// Slack app credentials (connection_type = oauth)
+    #[validate(length(min = 1, max = 255))]
+    pub client_id: Option<String>,
+    #[validate(length(min = 1, max = 255))]
+    pub client_secret: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct UpdateAlertChannelRequest {
+    #[validate(length(min = 1, max = 255))]
+    pub name: Option<String>,
+    /// Replace the incoming webhook URL (reactivates a channel in error)
+    #[validate(length(min = 1, max = 2048))]
+    pub webhook_url: Option<String>,
+    #[validate(length(min = 1, max = 255))]
+    pub channel_name: Option<String>,
+    pub is_active: Option<bool>,
+}
+
+/// Redirect parameters handed back by the frontend after the Slack app was installed
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CompleteAlertChannelAuthorizationRequest {
+    pub code: String,
+    pub state: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AlertChannelAuthorizationResponse {
+    pub authorization_url: String,
+    pub channel: AlertChannel,
+}
+
+// ============================================================================
+// MESSAGE FORMATTING
+// ============================================================================
+
+/// Headline emoji per alert type
+pub fn alert_emoji(alert_type: &str) -> &'static str {
+    match alert_type {
+        "expiry_critical" => "🚨",
+        "expiry_warning" => "⏳",
+        "low_stock" => "📉",
+        "watchlist_match" => "🔎",
+        "price_drop" => "💲",
+        "back_in_stock" => "📦",
+        "new_inquiry" => "📨",
+        "inquiry_message" => "💬",
+        "catalog_change" => "📋",
+        "report_ready" => "📊",
+        "digest" => "🗞️",
+        "announcement" => "📢",
+        "dispute" => "⚖️",
+        "license" => "🪪",
+        _ => "🔔",
+    }
+}
+
+/// Labelled facts shown under the message, picked from the alert metadata per alert type
+pub fn alert_fields(alert_type: &str, metadata: Option<&Value>) -> Vec<(&'static str, String)> {
+    let Some(metadata) = metadata else {
+        return Vec::new();
+    };
+    let field = |key: &str| -> Option<String> {
+        match metadata.get(key)? {
+            Value::Null => None,
+            Value::String(s) => Some(s.clone()),
+            other => Some(other.to_string()),
+        }
+    };
+
+    let wanted: &[(&'static str, &str)] = match alert_type {
+        "expiry_warning" | "expiry_critical" => &[
+            ("Product", "product_name"),
+            ("Days to expiry", "days_to_expiry"),
+            ("Quantity", "quantity"),
+        ],
+        "low_stock" => &[
+            ("Product", "product_name"),
+            ("On hand", "current_quantity"),
+            ("Threshold", "threshold"),
+        ],
+        "watchlist_match" => &[("Watchlist", "watchlist_name"), ("New matches", "match_count")],
+        "price_drop" => &[
+            ("Product", "product_name"),
+            ("NDC", "ndc_code"),
+            ("Best price", "best_price"),
+            ("Previous", "previous_price"),
+            ("Target", "threshold"),
+        ],
+        "back_in_stock" => &[
+            ("Product", "product_name"),
+            ("NDC", "ndc_code"),
+            ("Listings", "listing_count"),
+            ("Best price", "best_price"),
+        ],
+        "new_inquiry" => &[
+            ("Buyer", "buyer_company"),
+            ("Product", "product_name"),
+            ("Quantity", "quantity"),
+        ],
+        "inquiry_message" => &[("From", "sender_company")],
+        "catalog_change" => &[
+            ("Source", "source"),
+            ("Identifier", "identifier"),
+            ("Change", "change_type"),
+            ("Previous", "old_value"),
+            ("Current", "new_value"),
+        ],
+        "report_ready" => &[("Rows", "row_count"), ("Format", "format"), ("Reason", "reason")],
+        "dispute" => &[
+            ("From", "buyer_company"),
+            ("Product", "product_name"),
+            ("Excursions", "excursion_count"),
+            ("Outcome", "outcome"),
+        ],
+        "license" => &[
+            ("License", "license_type"),
+            ("Number", "license_number"),
+            ("Expires", "expires_on"),
+            ("Status", "status"),
+            ("Reason", "reason"),
+        ],
+        "system" => &[("Source", "source")],
+        _ => &[],
+    };
+
+    wanted
+        .iter()
+        .filter_map(|(label, key)| field(key).map(|value| (*label, value)))
+        .collect()
+}
+
+/// Absolute link for the alert's in-app action URL, when a frontend base URL is configured
+pub fn alert_link(alert: &AlertNotification, frontend_base_url: Option<&str>) -> Option<String> {
+    let action_url = alert.action_url.as_deref()?;
+    if action_url.starts_with("https://") {
+        return Some(action_url.to_string());
+    }
+    let base = frontend_base_url?.trim_end_matches('/');
+    Some(format!("{}/{}", base, action_url.trim_start_matches('/')))
+}
+
+/// Slack mrkdwn treats &, < and > as control characters
+fn escape_slack(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Slack Block Kit message for an alert
+pub fn slack_message(alert: &AlertNotification, link: Option<&str>) -> Value {
+    let emoji = alert_emoji(&alert.alert_type);
+    let mut blocks = vec![
+        json!({
+            "type": "header",
+            "text": { "type": "plain_text", "text": format!("{} {}", emoji, alert.title), "emoji": true }
+        }),
+        json!({
+            "type": "section",
+            "text": { "type": "mrkdwn", "text": escape_slack(&alert.message) }
+        }),
+    ];
+
+    let fields: Vec<Value> = alert_fields(&alert.alert_type, alert.metadata.as_ref())
+        .into_iter()
+        .map(|(label, value)| json!({ "type": "mrkdwn", "text": format!("*{}*\n{}", label, escape_slack(&value)) }))
+        .collect();
+    // Slack allows at most 10 fields per section
+    for chunk in fields.chunks(10) {
+        blocks.push(json!({ "type": "section", "fields": chunk }));
+    }
+
+    if let Some(link) = link {
+        blocks.push(json!({
+            "type": "actions",
+            "elements": [{
+                "type": "button",
+                "text": { "type": "plain_text", "text": "Open in Atlas" },
+                "url": link,
+            }]
+        }));
+    }
+
+    blocks.push(json!({
+        "type": "context",
+        "elements": [{
+            "type": "mrkdwn",
+            "text": format!("{} · {} · {}", alert.severity.to_uppercase(), alert.alert_type, alert.created_at.format("%Y-%m-%d %H:%M UTC"))
+        }]
+    }));
+
+    json!({
+        // Notification/fallback text
+        "text": format!("{} {}", emoji, alert.title),
+        "blocks": blocks,
+    })
+}
+
+/// Microsoft Teams message (Adaptive Card attachment) for an alert; accepted by both
+/// Office 365 connector webhooks and Workflows webhook triggers
+pub fn teams_message(alert: &AlertNotification, link: Option<&str>) -> Value {
+    let color = match alert.severity.as_str() {
+        "critical" => "Attention",
+        "warning" => "Warning",
+        _ => "Accent",
+    };
+
+    let mut body = vec![
+        json!({
+            "type": "TextBlock",
+            "text": format!("{} {}", alert_emoji(&alert.alert_type), alert.title),
+            "weight": "Bolder",
+            "size": "Medium",
+            "color": color,
+            "wrap": true,
+        }),
+        json!({ "type": "TextBlock", "text": alert.message, "wrap": true }),
+    ];
+
+    let facts: Vec<Value> = alert_fields(&alert.alert_type, alert.metadata.as_ref())
+        .into_iter()
+        .map(|(label, value)| json!({ "title": label, "value": value }))
+        .collect();
+    if !facts.is_empty() {
+        body.push(json!({ "type": "FactSet", "facts": facts }));
+    }
+
+    body.push(json!({
+        "type": "TextBlock",
+        "text": format!("{} · {} · {}", alert.severity.to_uppercase(), alert.alert_type, alert.created_at.format("%Y-%m-%d %H:%M UTC")),
+        "isSubtle": true,
+        "size": "Small",
+        "wrap": true,
+    }));
+
+    let actions: Vec<Value> = link
+        .map(|link| json!({ "type": "Action.OpenUrl", "title": "Open in Atlas", "url": link }))
+        .into_iter()
+        .collect();
+
+    json!({
+        "type": "message",
+        "attachments": [{
+            "contentType": "application/vnd.microsoft.card.adaptive",
+            "content": {
+                "$schema": "http://adaptivecards.io/schemas/adaptive-card.json",
+                "type": "AdaptiveCard",
+                "version": "1.4",
+                "body": body,
+                "actions": actions,
+            }
+        }]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notification(alert_type: &str, severity: &str, metadata: Value) -> AlertNotification {
+        AlertNotification {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            alert_type: alert_type.to_string(),
+            severity: severity.to_string(),
+            title: "Low Stock: Amoxicillin <500mg>".to_string(),
+            message: "Running low & reorder soon".to_string(),
+            inventory_id: None,
+            related_user_id: None,
+            metadata: Some(metadata),
+            action_url: Some("/dashboard/inventory?highlight=1".to_string()),
+            is_read: false,
+            is_dismissed: false,
+            created_at: Utc::now(),
+            read_at: None,
+            dismissed_at: None,
+            held_until: None,
+            digest_id: None,
+            archived_at: None,
+        }
+    }
+
+    #[test]
+    fn test_webhook_hosts() {
+        assert!(ChatProvider::Slack.is_webhook_host("hooks.slack.com"));
+        assert!(!ChatProvider::Slack.is_webhook_host("hooks.slack.com.evil.example"));
+        assert!(ChatProvider::Teams.is_webhook_host("contoso.webhook.office.com"));
+        assert!(ChatProvider::Teams.is_webhook_host("prod-12.westus.logic.azure.com"));
+        assert!(!ChatProvider::Teams.is_webhook_host("hooks.slack.com"));
+    }
+
+    #[test]
+    fn test_fields_follow_alert_type() {
+        let metadata = json!({ "product_name": "Amoxicillin", "current_quantity": 4, "threshold": 10, "buyer_company": "X" });
+        let fields = alert_fields("low_stock", Some(&metadata));
+        assert_eq!(
+            fields,
+            vec![("Product", "Amoxicillin".to_string()), ("On hand", "4".to_string()), ("Threshold", "10".to_string())]
+        );
+
+        let catalog = json!({ "source": "ema", "change_type": "recall", "old_value": null });
+        let fields = alert_fields("catalog_change", Some(&catalog));
+        assert_eq!(fields.len(), 2);
+        assert!(alert_fields("low_stock", None).is_empty());
+    }
+
+    #[test]
+    fn test_slack_message_escapes_and_links() {
+        let alert = notification("low_stock", "warning", json!({ "product_name": "A&B", "threshold": 10 }));
+        let link = alert_link(&alert, Some("https://app.example.com/"));
+        assert_eq!(link.as_deref(), Some("https://app.example.com/dashboard/inventory?highlight=1"));
+
+        let message = slack_message(&alert, link.as_deref());
+        let blocks = message["blocks"].as_array().unwrap();
+        assert_eq!(blocks[1]["text"]["text"], "Running low &amp; reorder soon");
+        assert_eq!(blocks[2]["fields"][0]["text"], "*Product*\nA&amp;B");
+        assert!(blocks.iter().any(|b| b["type"] == "actions"));
+
+        assert!(alert_link(&alert, None).is_none());
+        let message = slack_message(&alert, None);
+        assert!(!message["blocks"].as_array().unwrap().iter().any(|b| b["type"] == "actions"));
+    }
+
+    #[test]
+    fn test_teams_message_card() {
+        let alert = notification("expiry_critical", "critical", json!({ "product_name": "A", "days_to_expiry": 5 }));
+        let message = teams_message(&alert, None);
+        let card = &message["attachments"][0]["content"];
+        assert_eq!(card["type"], "AdaptiveCard");
+        assert_eq!(card["body"][0]["color"], "Attention");
+        assert_eq!(card["body"][2]["facts"][1]["value"], "5");
+        assert!(card["actions"].as_array().unwrap().is_empty());
+    }
+}
@@ -6,16 +6,19 @@
 /// - Marketplace watchlist
 /// - Alert processing logs
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Datelike, Duration, NaiveDateTime, Timelike, Utc};
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 use sqlx::FromRow;
+use std::collections::HashMap;
 use uuid::Uuid;
 
 // ============================================================================
 // ENUMS
 // ============================================================================
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, ToSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum AlertType {
     ExpiryWarning,
@@ -23,8 +26,15 @@ pub enum AlertType {
     LowStock,
     WatchlistMatch,
     PriceDrop,
+    BackInStock,
     NewInquiry,
     InquiryMessage,
+    CatalogChange,
+    ReportReady,
+    Digest,
+    Announcement,
+    Dispute,
+    License,
     System,
 }
 
@@ -36,8 +46,15 @@ impl AlertType {
             AlertType::LowStock => "low_stock",
             AlertType::WatchlistMatch => "watchlist_match",
             AlertType::PriceDrop => "price_drop",
+            AlertType::BackInStock => "back_in_stock",
             AlertType::NewInquiry => "new_inquiry",
             AlertType::InquiryMessage => "inquiry_message",
+            AlertType::CatalogChange => "catalog_change",
+            AlertType::ReportReady => "report_ready",
+            AlertType::Digest => "digest",
+            AlertType::Announcement => "announcement",
+            AlertType::Dispute => "dispute",
+            AlertType::License => "license",
             AlertType::System => "system",
         }
     }
@@ -49,7 +66,7 @@ impl std::fmt::Display for AlertType {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum AlertSeverity {
     Info,
@@ -73,11 +90,99 @@ impl std::fmt::Display for AlertSeverity {
     }
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub enum WatchlistCheckFrequency {
+    #[serde(rename = "15min")]
+    FifteenMinutes,
+    #[serde(rename = "hourly")]
+    Hourly,
+    #[serde(rename = "daily")]
+    Daily,
+}
+
+impl WatchlistCheckFrequency {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WatchlistCheckFrequency::FifteenMinutes => "15min",
+            WatchlistCheckFrequency::Hourly => "hourly",
+            WatchlistCheckFrequency::Daily => "daily",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "15min" => Some(WatchlistCheckFrequency::FifteenMinutes),
+            "hourly" => Some(WatchlistCheckFrequency::Hourly),
+            "daily" => Some(WatchlistCheckFrequency::Daily),
+            _ => None,
+        }
+    }
+
+    pub fn interval(&self) -> Duration {
+        match self {
+            WatchlistCheckFrequency::FifteenMinutes => Duration::minutes(15),
+            WatchlistCheckFrequency::Hourly => Duration::hours(1),
+            WatchlistCheckFrequency::Daily => Duration::days(1),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WatchlistTriggerType {
+    PriceDrop,
+    BackInStock,
+}
+
+impl WatchlistTriggerType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WatchlistTriggerType::PriceDrop => "price_drop",
+            WatchlistTriggerType::BackInStock => "back_in_stock",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "price_drop" => Some(WatchlistTriggerType::PriceDrop),
+            "back_in_stock" => Some(WatchlistTriggerType::BackInStock),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DigestFrequency {
+    Off,
+    Daily,
+    Weekly,
+}
+
+impl DigestFrequency {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DigestFrequency::Off => "off",
+            DigestFrequency::Daily => "daily",
+            DigestFrequency::Weekly => "weekly",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "off" => Some(DigestFrequency::Off),
+            "daily" => Some(DigestFrequency::Daily),
+            "weekly" => Some(DigestFrequency::Weekly),
+            _ => None,
+        }
+    }
+}
+
 // ============================================================================
 // DATABASE MODELS
 // ============================================================================
 
-#[derive(Debug, Clone, FromRow, Serialize)]
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
 pub struct UserAlertPreferences {
     pub user_id: Uuid,
     pub expiry_alerts_enabled: bool,
@@ -87,6 +192,16 @@ pub struct UserAlertPreferences {
     pub watchlist_alerts_enabled: bool,
     pub email_notifications_enabled: bool,
     pub in_app_notifications_enabled: bool,
+    /// Alert type -> ids of the Slack/Teams channels the alert is posted to
+    pub channel_routes: serde_json::Value,
+    pub digest_frequency: String,
+    pub digest_hour: i32,
+    /// ISO weekday of weekly digests (1 = Monday)
+    pub digest_weekday: i32,
+    pub quiet_hours_enabled: bool,
+    pub quiet_hours_start: i32,
+    pub quiet_hours_end: i32,
+    pub timezone: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -108,6 +223,11 @@ pub struct AlertNotification {
     pub created_at: DateTime<Utc>,
     pub read_at: Option<DateTime<Utc>>,
     pub dismissed_at: Option<DateTime<Utc>>,
+    /// Set while held back for a digest or quiet hours
+    pub held_until: Option<DateTime<Utc>>,
+    pub digest_id: Option<Uuid>,
+    /// Set by the retention job
+    pub archived_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone, FromRow, Serialize)]
@@ -123,6 +243,28 @@ pub struct MarketplaceWatchlist {
     pub total_matches_found: i32,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub check_frequency: String,
+    pub paused_at: Option<DateTime<Utc>>,
+    pub next_check_at: DateTime<Utc>,
+}
+
+/// Price drop / back in stock trigger on a watched NDC
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct WatchlistTrigger {
+    pub id: Uuid,
+    pub watchlist_id: Uuid,
+    pub trigger_type: String,
+    pub ndc_code: String,
+    pub price_threshold: Option<Decimal>,
+    pub cooldown_hours: i32,
+    pub is_active: bool,
+    pub last_best_price: Option<Decimal>,
+    pub last_in_stock: Option<bool>,
+    pub last_evaluated_at: Option<DateTime<Utc>>,
+    pub last_triggered_at: Option<DateTime<Utc>>,
+    pub trigger_count: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, FromRow, Serialize)]
@@ -142,7 +284,7 @@ pub struct AlertProcessingLog {
 // API REQUEST MODELS
 // ============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateAlertPreferencesRequest {
     pub expiry_alerts_enabled: Option<bool>,
     pub expiry_alert_days: Option<i32>,
@@ -151,30 +293,75 @@ pub struct UpdateAlertPreferencesRequest {
     pub watchlist_alerts_enabled: Option<bool>,
     pub email_notifications_enabled: Option<bool>,
     pub in_app_notifications_enabled: Option<bool>,
+    /// Replaces all routes; alert types left out are not posted to any channel
+    pub channel_routes: Option<HashMap<AlertType, Vec<Uuid>>>,
+    pub digest_frequency: Option<DigestFrequency>,
+    pub digest_hour: Option<i32>,
+    pub digest_weekday: Option<i32>,
+    pub quiet_hours_enabled: Option<bool>,
+    pub quiet_hours_start: Option<i32>,
+    pub quiet_hours_end: Option<i32>,
+    /// IANA timezone name, e.g. "Europe/Berlin"
+    pub timezone: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+impl UpdateAlertPreferencesRequest {
+    /// Whether the update changes when held notifications are due
+    pub fn changes_delivery_schedule(&self) -> bool {
+        self.digest_frequency.is_some()
+            || self.digest_hour.is_some()
+            || self.digest_weekday.is_some()
+            || self.quiet_hours_enabled.is_some()
+            || self.quiet_hours_start.is_some()
+            || self.quiet_hours_end.is_some()
+            || self.timezone.is_some()
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateWatchlistRequest {
     pub name: String,
     pub description: Option<String>,
     pub search_criteria: serde_json::Value,
     pub alert_enabled: Option<bool>,
+    pub check_frequency: Option<WatchlistCheckFrequency>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateWatchlistRequest {
     pub name: Option<String>,
     pub description: Option<String>,
     pub search_criteria: Option<serde_json::Value>,
     pub alert_enabled: Option<bool>,
+    pub check_frequency: Option<WatchlistCheckFrequency>,
+    /// Pause (true) or resume (false) evaluation
+    pub paused: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateWatchlistTriggerRequest {
+    pub trigger_type: WatchlistTriggerType,
+    pub ndc_code: String,
+    /// Required for price_drop: alert when the best price is at or below this unit price
+    pub price_threshold: Option<Decimal>,
+    /// Minimum hours between two alerts of this trigger (default 24)
+    pub cooldown_hours: Option<i32>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateWatchlistTriggerRequest {
+    pub price_threshold: Option<Decimal>,
+    pub cooldown_hours: Option<i32>,
+    pub is_active: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct MarkAlertReadRequest {
     pub is_read: bool,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct GetNotificationsQuery {
     pub limit: Option<i64>,
     pub offset: Option<i64>,
@@ -186,7 +373,7 @@ pub struct GetNotificationsQuery {
 // API RESPONSE MODELS
 // ============================================================================
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct AlertNotificationResponse {
     pub id: Uuid,
     pub alert_type: String,
@@ -199,6 +386,8 @@ pub struct AlertNotificationResponse {
     pub is_read: bool,
     pub created_at: DateTime<Utc>,
     pub time_ago: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archived_at: Option<DateTime<Utc>>,
 }
 
 impl From<AlertNotification> for AlertNotificationResponse {
@@ -215,25 +404,60 @@ impl From<AlertNotification> for AlertNotificationResponse {
             is_read: notif.is_read,
             time_ago: format_time_ago(notif.created_at),
             created_at: notif.created_at,
+            archived_at: notif.archived_at,
         }
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct NotificationSummary {
     pub total_unread: i64,
     pub total_notifications: i64,
     pub notifications: Vec<AlertNotificationResponse>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ArchivedNotificationsQuery {
+    pub page: Option<i64>,
+    pub page_size: Option<i64>,
+    pub alert_type: Option<AlertType>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ArchivedNotificationsResponse {
+    pub notifications: Vec<AlertNotificationResponse>,
+    pub total: i64,
+    pub page: i64,
+    pub page_size: i64,
+}
+
+/// Dismiss active notifications in bulk; at least one filter is required
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BulkDismissRequest {
+    #[serde(default)]
+    pub alert_types: Vec<AlertType>,
+    /// Only notifications created before this time
+    pub before: Option<DateTime<Utc>>,
+    /// Leave unread notifications alone
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct WatchlistResponse {
     pub id: Uuid,
     pub name: String,
     pub description: Option<String>,
     pub search_criteria: serde_json::Value,
     pub alert_enabled: bool,
+    pub check_frequency: String,
+    pub is_paused: bool,
+    pub paused_at: Option<DateTime<Utc>>,
+    /// Last evaluation
     pub last_checked_at: DateTime<Utc>,
+    /// Next scheduled evaluation (None while paused)
+    pub next_check_at: Option<DateTime<Utc>>,
     pub last_match_count: i32,
     pub total_matches_found: i32,
     pub created_at: DateTime<Utc>,
@@ -247,7 +471,11 @@ impl From<MarketplaceWatchlist> for WatchlistResponse {
             description: watchlist.description,
             search_criteria: watchlist.search_criteria,
             alert_enabled: watchlist.alert_enabled,
+            check_frequency: watchlist.check_frequency,
+            is_paused: watchlist.paused_at.is_some(),
+            paused_at: watchlist.paused_at,
             last_checked_at: watchlist.last_checked_at,
+            next_check_at: watchlist.paused_at.is_none().then_some(watchlist.next_check_at),
             last_match_count: watchlist.last_match_count,
             total_matches_found: watchlist.total_matches_found,
             created_at: watchlist.created_at,
@@ -364,6 +592,84 @@ impl AlertPayload {
         }
     }
 
+    /// Create a price drop notification for a watchlist trigger
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_price_drop(
+        user_id: Uuid,
+        watchlist_name: &str,
+        ndc_code: &str,
+        product_name: &str,
+        best_price: Decimal,
+        threshold: Decimal,
+        previous_price: Option<Decimal>,
+        inventory_id: Option<Uuid>,
+    ) -> Self {
+        Self {
+            user_id,
+            alert_type: AlertType::PriceDrop,
+            severity: AlertSeverity::Info,
+            title: format!("Price drop: {} now ${}", product_name, best_price),
+            message: format!(
+                "The best marketplace price for {} (NDC {}) is ${}{}, at or below your ${} target on watchlist \"{}\".",
+                product_name,
+                ndc_code,
+                best_price,
+                previous_price
+                    .map(|p| format!(" (was ${})", p))
+                    .unwrap_or_default(),
+                threshold,
+                watchlist_name
+            ),
+            inventory_id,
+            related_user_id: None,
+            metadata: Some(serde_json::json!({
+                "watchlist_name": watchlist_name,
+                "ndc_code": ndc_code,
+                "product_name": product_name,
+                "best_price": best_price,
+                "previous_price": previous_price,
+                "threshold": threshold,
+            })),
+            action_url: Some(format!("/dashboard/marketplace?ndc={}", ndc_code)),
+        }
+    }
+
+    /// Create a back in stock notification for a watchlist trigger
+    pub fn new_back_in_stock(
+        user_id: Uuid,
+        watchlist_name: &str,
+        ndc_code: &str,
+        product_name: &str,
+        listing_count: i64,
+        best_price: Option<Decimal>,
+        inventory_id: Option<Uuid>,
+    ) -> Self {
+        Self {
+            user_id,
+            alert_type: AlertType::BackInStock,
+            severity: AlertSeverity::Info,
+            title: format!("Back in stock: {}", product_name),
+            message: format!(
+                "{} (NDC {}) is listed again on the marketplace: {} listing{}{}.",
+                product_name,
+                ndc_code,
+                listing_count,
+                if listing_count == 1 { "" } else { "s" },
+                best_price.map(|p| format!(", from ${}", p)).unwrap_or_default()
+            ),
+            inventory_id,
+            related_user_id: None,
+            metadata: Some(serde_json::json!({
+                "watchlist_name": watchlist_name,
+                "ndc_code": ndc_code,
+                "product_name": product_name,
+                "listing_count": listing_count,
+                "best_price": best_price,
+            })),
+            action_url: Some(format!("/dashboard/marketplace?ndc={}", ndc_code)),
+        }
+    }
+
     /// Create a new inquiry notification for the seller
     pub fn new_inquiry(
         seller_id: Uuid,
@@ -417,6 +723,790 @@ impl AlertPayload {
             action_url: Some(format!("/dashboard/inquiries?id={}", inquiry_id)),
         }
     }
+
+    /// Notify the seller of a dispute opened against a shipment
+    pub fn new_dispute_opened(
+        seller_id: Uuid,
+        buyer_id: Uuid,
+        buyer_company: &str,
+        transaction_id: Uuid,
+        dispute_id: Uuid,
+        product_name: &str,
+        excursion_count: i32,
+    ) -> Self {
+        Self {
+            user_id: seller_id,
+            alert_type: AlertType::Dispute,
+            severity: AlertSeverity::Warning,
+            title: format!("Temperature excursion dispute: {}", product_name),
+            message: format!(
+                "{} opened a dispute over {} temperature excursion{} recorded during shipment of {}.",
+                buyer_company,
+                excursion_count,
+                if excursion_count == 1 { "" } else { "s" },
+                product_name
+            ),
+            inventory_id: None,
+            related_user_id: Some(buyer_id),
+            metadata: Some(serde_json::json!({
+                "transaction_id": transaction_id,
+                "dispute_id": dispute_id,
+                "buyer_company": buyer_company,
+                "product_name": product_name,
+                "excursion_count": excursion_count,
+            })),
+            action_url: Some(format!("/dashboard/transactions?id={}&dispute={}", transaction_id, dispute_id)),
+        }
+    }
+
+    /// Notify the other party that a dispute was accepted, rejected or withdrawn
+    pub fn new_dispute_resolved(
+        user_id: Uuid,
+        resolved_by: Uuid,
+        transaction_id: Uuid,
+        dispute_id: Uuid,
+        outcome: &str,
+        resolution: &str,
+    ) -> Self {
+        Self {
+            user_id,
+            alert_type: AlertType::Dispute,
+            severity: AlertSeverity::Info,
+            title: format!("Dispute {}", outcome),
+            message: format!("The dispute over transaction {} was {}: {}", transaction_id, outcome, resolution),
+            inventory_id: None,
+            related_user_id: Some(resolved_by),
+            metadata: Some(serde_json::json!({
+                "transaction_id": transaction_id,
+                "dispute_id": dispute_id,
+                "outcome": outcome,
+            })),
+            action_url: Some(format!("/dashboard/transactions?id={}&dispute={}", transaction_id, dispute_id)),
+        }
+    }
+
+    /// Remind a company that one of its verified licenses expires soon
+    pub fn new_license_expiring(
+        user_id: Uuid,
+        license_id: Uuid,
+        license_label: &str,
+        license_number: &str,
+        expires_on: chrono::NaiveDate,
+        days_to_expiry: i64,
+    ) -> Self {
+        let severity = if days_to_expiry <= 7 {
+            AlertSeverity::Critical
+        } else {
+            AlertSeverity::Warning
+        };
+
+        Self {
+            user_id,
+            alert_type: AlertType::License,
+            severity,
+            title: format!("{} expires in {} days", license_label, days_to_expiry),
+            message: format!(
+                "{} {} expires on {}. Upload the renewed license before then; listing rights are suspended once it has expired.",
+                license_label, license_number, expires_on
+            ),
+            inventory_id: None,
+            related_user_id: None,
+            metadata: Some(serde_json::json!({
+                "license_id": license_id,
+                "license_type": license_label,
+                "license_number": license_number,
+                "expires_on": expires_on,
+                "days_to_expiry": days_to_expiry,
+            })),
+            action_url: Some(format!("/dashboard/licenses?id={}", license_id)),
+        }
+    }
+
+    /// Tell a company the outcome of an admin review of its license
+    pub fn new_license_reviewed(
+        user_id: Uuid,
+        license_id: Uuid,
+        license_label: &str,
+        license_number: &str,
+        status: &str,
+        notes: Option<&str>,
+    ) -> Self {
+        let severity = if status == "verified" {
+            AlertSeverity::Info
+        } else {
+            AlertSeverity::Warning
+        };
+        let mut message = format!("{} {} was {}.", license_label, license_number, status);
+        if let Some(notes) = notes {
+            message.push_str(&format!(" {}", notes));
+        }
+
+        Self {
+            user_id,
+            alert_type: AlertType::License,
+            severity,
+            title: format!("{} {}", license_label, status),
+            message,
+            inventory_id: None,
+            related_user_id: None,
+            metadata: Some(serde_json::json!({
+                "license_id": license_id,
+                "license_type": license_label,
+                "license_number": license_number,
+                "status": status,
+            })),
+            action_url: Some(format!("/dashboard/licenses?id={}", license_id)),
+        }
+    }
+
+    /// Listing rights were suspended (`reason`) or, with None, restored
+    pub fn new_listing_rights_changed(user_id: Uuid, reason: Option<&str>) -> Self {
+        let (severity, title, message) = match reason {
+            Some(reason) => (
+                AlertSeverity::Critical,
+                "Listing rights suspended".to_string(),
+                format!(
+                    "{}. Your listings are hidden from the marketplace and you can't list inventory until a renewed license is verified.",
+                    reason
+                ),
+            ),
+            None => (
+                AlertSeverity::Info,
+                "Listing rights restored".to_string(),
+                "Your licenses are valid again; your listings are visible on the marketplace.".to_string(),
+            ),
+        };
+
+        Self {
+            user_id,
+            alert_type: AlertType::License,
+            severity,
+            title,
+            message,
+            inventory_id: None,
+            related_user_id: None,
+            metadata: Some(serde_json::json!({
+                "listing_suspended": reason.is_some(),
+                "reason": reason,
+            })),
+            action_url: Some("/dashboard/licenses".to_string()),
+        }
+    }
+
+    /// The account was suspended by an admin (`until` None: indefinitely)
+    pub fn new_account_suspended(user_id: Uuid, reason: &str, until: Option<DateTime<Utc>>) -> Self {
+        let duration = match until {
+            Some(until) => format!("until {}", until.format("%Y-%m-%d %H:%M UTC")),
+            None => "until further notice".to_string(),
+        };
+
+        Self {
+            user_id,
+            alert_type: AlertType::System,
+            severity: AlertSeverity::Critical,
+            title: "Account suspended".to_string(),
+            message: format!(
+                "Your account is suspended {}: {}. You can't sign in and your listings are hidden from the marketplace.",
+                duration, reason
+            ),
+            inventory_id: None,
+            related_user_id: None,
+            metadata: Some(serde_json::json!({
+                "suspended": true,
+                "reason": reason,
+                "suspended_until": until,
+            })),
+            action_url: None,
+        }
+    }
+
+    /// The account's suspension was lifted
+    pub fn new_account_reinstated(user_id: Uuid) -> Self {
+        Self {
+            user_id,
+            alert_type: AlertType::System,
+            severity: AlertSeverity::Info,
+            title: "Account reinstated".to_string(),
+            message: "Your suspension has ended; your listings are visible on the marketplace again.".to_string(),
+            inventory_id: None,
+            related_user_id: None,
+            metadata: Some(serde_json::json!({ "suspended": false })),
+            action_url: Some("/dashboard/inventory".to_string()),
+        }
+    }
+
+    /// The account was verified, or (with a reason) its verification was rejected
+    pub fn new_verification_decided(user_id: Uuid, rejection_reason: Option<&str>) -> Self {
+        let (severity, title, message, action_url) = match rejection_reason {
+            Some(reason) => (
+                AlertSeverity::Warning,
+                "Account verification rejected".to_string(),
+                format!(
+                    "Your account could not be verified: {}. Upload your current licenses to apply again.",
+                    reason
+                ),
+                "/dashboard/licenses",
+            ),
+            None => (
+                AlertSeverity::Info,
+                "Account verified".to_string(),
+                "Your account is verified; you can now list inventory and trade on the marketplace.".to_string(),
+                "/dashboard/inventory",
+            ),
+        };
+
+        Self {
+            user_id,
+            alert_type: AlertType::System,
+            severity,
+            title,
+            message,
+            inventory_id: None,
+            related_user_id: None,
+            metadata: Some(serde_json::json!({
+                "verified": rejection_reason.is_none(),
+                "reason": rejection_reason,
+            })),
+            action_url: Some(action_url.to_string()),
+        }
+    }
+
+    /// Listings of the company were taken down by an admin
+    pub fn new_listings_taken_down(user_id: Uuid, inventory_ids: &[Uuid], reason: &str) -> Self {
+        Self {
+            user_id,
+            alert_type: AlertType::System,
+            severity: AlertSeverity::Warning,
+            title: format!("{} listing(s) taken down", inventory_ids.len()),
+            message: format!(
+                "{} of your listings were removed from the marketplace: {}.",
+                inventory_ids.len(),
+                reason
+            ),
+            inventory_id: if inventory_ids.len() == 1 { Some(inventory_ids[0]) } else { None },
+            related_user_id: None,
+            metadata: Some(serde_json::json!({
+                "inventory_ids": inventory_ids,
+                "reason": reason,
+            })),
+            action_url: Some("/dashboard/inventory".to_string()),
+        }
+    }
+
+    /// Create a catalog change notification for a subscribed record
+    pub fn new_catalog_change(
+        user_id: Uuid,
+        source: &str,
+        identifier: &str,
+        product_name: &str,
+        change_type: &str,
+        old_value: Option<&str>,
+        new_value: Option<&str>,
+    ) -> Self {
+        let severity = match change_type {
+            "recall" => AlertSeverity::Critical,
+            "discontinuation" => AlertSeverity::Warning,
+            _ => AlertSeverity::Info,
+        };
+
+        let change_label = match change_type {
+            "recall" => "Recall or suspension",
+            "discontinuation" => "Discontinued",
+            _ => "Status changed",
+        };
+
+        Self {
+            user_id,
+            alert_type: AlertType::CatalogChange,
+            severity,
+            title: format!("{}: {} ({})", change_label, product_name, identifier),
+            message: format!(
+                "{} changed from \"{}\" to \"{}\" in the latest {} catalog sync.",
+                product_name,
+                old_value.unwrap_or("none"),
+                new_value.unwrap_or("none"),
+                if source == "ema" { "EMA" } else { "OpenFDA" }
+            ),
+            inventory_id: None,
+            related_user_id: None,
+            metadata: Some(serde_json::json!({
+                "source": source,
+                "identifier": identifier,
+                "change_type": change_type,
+                "old_value": old_value,
+                "new_value": new_value,
+            })),
+            action_url: Some(format!("/dashboard/catalog?source={}&id={}", source, identifier)),
+        }
+    }
+
+    /// Create an admin notification for a catalog sync marked suspect
+    pub fn new_sync_anomaly(
+        user_id: Uuid,
+        source: &str,
+        sync_log_id: Uuid,
+        descriptions: &[String],
+        anomalies: serde_json::Value,
+    ) -> Self {
+        Self {
+            user_id,
+            alert_type: AlertType::System,
+            severity: AlertSeverity::Critical,
+            title: format!("Suspect {} catalog sync", source),
+            message: format!(
+                "The {} sync {} was marked suspect and will not count as a successful refresh: {}",
+                source,
+                sync_log_id,
+                descriptions.join("; ")
+            ),
+            inventory_id: None,
+            related_user_id: None,
+            metadata: Some(serde_json::json!({
+                "source": source,
+                "sync_log_id": sync_log_id,
+                "anomalies": anomalies,
+            })),
+            action_url: Some(format!("/admin/catalog-sync?source={}&sync_id={}", source, sync_log_id)),
+        }
+    }
+
+    /// Create an admin notification for high-severity marketplace anomalies
+    pub fn new_anomalies_detected(user_id: Uuid, descriptions: &[String], anomaly_ids: &[Uuid]) -> Self {
+        Self {
+            user_id,
+            alert_type: AlertType::System,
+            severity: AlertSeverity::Critical,
+            title: format!(
+                "{} high-severity marketplace anomal{} detected",
+                anomaly_ids.len(),
+                if anomaly_ids.len() == 1 { "y" } else { "ies" }
+            ),
+            message: descriptions.join("; "),
+            inventory_id: None,
+            related_user_id: None,
+            metadata: Some(serde_json::json!({
+                "anomaly_ids": anomaly_ids,
+            })),
+            action_url: Some("/admin/anomalies".to_string()),
+        }
+    }
+
+    /// Create an admin notification for a sanctions screening awaiting review
+    pub fn new_sanctions_hit(
+        user_id: Uuid,
+        screening_id: Uuid,
+        screened_user_id: Uuid,
+        company_name: &str,
+        top_score: f64,
+        outcome: &str,
+        transaction_id: Option<Uuid>,
+    ) -> Self {
+        let (severity, action) = if outcome == "blocked" {
+            (AlertSeverity::Critical, "transaction blocked")
+        } else {
+            (AlertSeverity::Warning, "flagged for review")
+        };
+
+        Self {
+            user_id,
+            alert_type: AlertType::System,
+            severity,
+            title: format!("Potential sanctions match: {}", company_name),
+            message: format!(
+                "{} matches a sanctions list entry (score {:.0}%); {}.",
+                company_name,
+                top_score * 100.0,
+                action
+            ),
+            inventory_id: None,
+            related_user_id: Some(screened_user_id),
+            metadata: Some(serde_json::json!({
+                "screening_id": screening_id,
+                "top_score": top_score,
+                "outcome": outcome,
+                "transaction_id": transaction_id,
+            })),
+            action_url: Some(format!("/admin/sanctions/screenings/{}", screening_id)),
+        }
+    }
+
+    /// Create an admin notification for audit log chain verification failures
+    pub fn new_audit_chain_issues(user_id: Uuid, issue_count: i64, from_seq: i64, head_seq: i64) -> Self {
+        Self {
+            user_id,
+            alert_type: AlertType::System,
+            severity: AlertSeverity::Critical,
+            title: "Audit log integrity check failed".to_string(),
+            message: format!(
+                "Chain verification of entries {} to {} found {} issue{}; the audit log may have been altered.",
+                from_seq,
+                head_seq,
+                issue_count,
+                if issue_count == 1 { "" } else { "s" }
+            ),
+            inventory_id: None,
+            related_user_id: None,
+            metadata: Some(serde_json::json!({
+                "issue_count": issue_count,
+                "from_seq": from_seq,
+                "head_seq": head_seq,
+            })),
+            action_url: Some("/admin/audit-logs/chain".to_string()),
+        }
+    }
+
+    /// Create a notification for a generated scheduled report
+    pub fn new_report_ready(
+        user_id: Uuid,
+        report_name: &str,
+        report_file_id: Uuid,
+        row_count: i32,
+        format: &str,
+    ) -> Self {
+        Self {
+            user_id,
+            alert_type: AlertType::ReportReady,
+            severity: AlertSeverity::Info,
+            title: format!("Report ready: {}", report_name),
+            message: format!(
+                "Your scheduled report \"{}\" is ready with {} row{} ({}).",
+                report_name,
+                row_count,
+                if row_count == 1 { "" } else { "s" },
+                format.to_uppercase()
+            ),
+            inventory_id: None,
+            related_user_id: None,
+            metadata: Some(serde_json::json!({
+                "report_file_id": report_file_id,
+                "row_count": row_count,
+                "format": format,
+            })),
+            action_url: Some(format!("/dashboard/reports?file={}", report_file_id)),
+        }
+    }
+
+    /// Create a notification for a scheduled report that could not be generated
+    pub fn new_report_skipped(user_id: Uuid, report_name: &str, schedule_id: Uuid, reason: &str) -> Self {
+        Self {
+            user_id,
+            alert_type: AlertType::ReportReady,
+            severity: AlertSeverity::Warning,
+            title: format!("Report not generated: {}", report_name),
+            message: format!("Your scheduled report \"{}\" was not generated: {}", report_name, reason),
+            inventory_id: None,
+            related_user_id: None,
+            metadata: Some(serde_json::json!({
+                "schedule_id": schedule_id,
+                "reason": reason,
+            })),
+            action_url: Some(format!("/dashboard/reports?schedule={}", schedule_id)),
+        }
+    }
+
+    /// Create one summary notification for held notifications released together
+    pub fn new_digest(user_id: Uuid, label: &str, notifications: &[AlertNotification]) -> Self {
+        let mut counts: Vec<(String, usize)> = Vec::new();
+        for notification in notifications {
+            match counts.iter_mut().find(|(t, _)| *t == notification.alert_type) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((notification.alert_type.clone(), 1)),
+            }
+        }
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        let severity = if notifications.iter().any(|n| n.severity != AlertSeverity::Info.as_str()) {
+            AlertSeverity::Warning
+        } else {
+            AlertSeverity::Info
+        };
+
+        Self {
+            user_id,
+            alert_type: AlertType::Digest,
+            severity,
+            title: format!(
+                "{}: {} notification{}",
+                label,
+                notifications.len(),
+                if notifications.len() == 1 { "" } else { "s" }
+            ),
+            message: counts
+                .iter()
+                .map(|(alert_type, count)| format!("{} {}", count, alert_type.replace('_', " ")))
+                .collect::<Vec<_>>()
+                .join(", "),
+            inventory_id: None,
+            related_user_id: None,
+            metadata: Some(serde_json::json!({
+                "notification_ids": notifications.iter().map(|n| n.id).collect::<Vec<_>>(),
+                "counts": counts.iter().cloned().collect::<HashMap<_, _>>(),
+                "titles": notifications.iter().take(10).map(|n| n.title.as_str()).collect::<Vec<_>>(),
+            })),
+            action_url: Some("/dashboard/alerts".to_string()),
+        }
+    }
+}
+
+// ============================================================================
+// WATCHLIST TRIGGERS
+// ============================================================================
+
+/// Current marketplace state of a watched NDC (listings from other sellers)
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarketSnapshot {
+    pub best_price: Option<Decimal>,
+    pub listing_count: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriggerEvaluation {
+    /// Alert now and record the snapshot
+    Fire,
+    /// Condition holds but the cooldown has not passed: keep the old state so the alert
+    /// goes out after the cooldown if the condition still holds
+    CoolingDown,
+    /// Nothing to report; record the snapshot
+    Idle,
+}
+
+/// Decide what a trigger does with the current snapshot. Price drop fires when the best
+/// price is at or below the threshold and either just crossed it or dropped further since
+/// the last check; back in stock fires when listings reappear after a check found none.
+pub fn evaluate_watchlist_trigger(
+    trigger: &WatchlistTrigger,
+    market: &MarketSnapshot,
+    now: DateTime<Utc>,
+) -> TriggerEvaluation {
+    let condition = match WatchlistTriggerType::parse(&trigger.trigger_type) {
+        Some(WatchlistTriggerType::PriceDrop) => match (market.best_price, trigger.price_threshold) {
+            (Some(price), Some(threshold)) if price <= threshold => match trigger.last_best_price {
+                None => true,
+                Some(previous) => previous > threshold || price < previous,
+            },
+            _ => false,
+        },
+        Some(WatchlistTriggerType::BackInStock) => market.listing_count > 0 && trigger.last_in_stock == Some(false),
+        None => false,
+    };
+
+    if !condition {
+        return TriggerEvaluation::Idle;
+    }
+
+    let cooling_down = trigger
+        .last_triggered_at
+        .map_or(false, |at| now < at + Duration::hours(trigger.cooldown_hours as i64));
+    if cooling_down {
+        TriggerEvaluation::CoolingDown
+    } else {
+        TriggerEvaluation::Fire
+    }
+}
+
+// ============================================================================
+// DIGESTS AND QUIET HOURS
+// ============================================================================
+
+/// Whether local `hour` falls in the quiet hours window [start, end); the window may
+/// wrap midnight (22 -> 7). Equal bounds mean no quiet hours.
+pub fn in_quiet_hours(hour: u32, start: u32, end: u32) -> bool {
+    if start == end {
+        false
+    } else if start < end {
+        hour >= start && hour < end
+    } else {
+        hour >= start || hour < end
+    }
+}
+
+/// First local time at or after `now` outside quiet hours
+pub fn quiet_hours_end_after(now: NaiveDateTime, start: u32, end: u32) -> NaiveDateTime {
+    if !in_quiet_hours(now.hour(), start, end) {
+        return now;
+    }
+    let end_today = now.date().and_hms_opt(end, 0, 0).unwrap_or(now);
+    if end_today > now {
+        end_today
+    } else {
+        end_today + Duration::days(1)
+    }
+}
+
+/// Next local digest time strictly after `now`: `hour`:00 every day, or on ISO `weekday`
+pub fn next_digest_at(now: NaiveDateTime, frequency: DigestFrequency, hour: u32, weekday: u32) -> Option<NaiveDateTime> {
+    let days_ahead = match frequency {
+        DigestFrequency::Off => return None,
+        DigestFrequency::Daily => 0,
+        DigestFrequency::Weekly => (weekday as i64 - now.weekday().number_from_monday() as i64).rem_euclid(7),
+    };
+    let candidate = (now.date() + Duration::days(days_ahead)).and_hms_opt(hour, 0, 0)?;
+    if candidate > now {
+        Some(candidate)
+    } else if frequency == DigestFrequency::Weekly {
+        Some(candidate + Duration::days(7))
+    } else {
+        Some(candidate + Duration::days(1))
+    }
+}
+
+/// Local time a notification created at local `now` is held until, or `None` to deliver
+/// it right away. Critical notifications always break through.
+pub fn hold_until(now: NaiveDateTime, preferences: &UserAlertPreferences, severity: &AlertSeverity) -> Option<NaiveDateTime> {
+    if *severity == AlertSeverity::Critical {
+        return None;
+    }
+
+    let frequency = DigestFrequency::parse(&preferences.digest_frequency).unwrap_or(DigestFrequency::Off);
+    let due = match frequency {
+        DigestFrequency::Off => now,
+        _ => next_digest_at(
+            now,
+            frequency,
+            preferences.digest_hour as u32,
+            preferences.digest_weekday as u32,
+        )?,
+    };
+    let due = if preferences.quiet_hours_enabled {
+        quiet_hours_end_after(due, preferences.quiet_hours_start as u32, preferences.quiet_hours_end as u32)
+    } else {
+        due
+    };
+
+    (due > now).then_some(due)
+}
+
+// ============================================================================
+// RETENTION
+// ============================================================================
+
+/// Notification retention: archive after `archive_after_days`, delete after
+/// `delete_after_days` (both counted from creation)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    pub archive_after_days: i64,
+    pub delete_after_days: i64,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            archive_after_days: 90,
+            delete_after_days: 365,
+        }
+    }
+}
+
+impl RetentionPolicy {
+    /// NOTIFICATION_ARCHIVE_AFTER_DAYS / NOTIFICATION_DELETE_AFTER_DAYS
+    pub fn from_env() -> Self {
+        let var = |name: &str| std::env::var(name).ok().and_then(|v| v.parse::<i64>().ok());
+        Self::new(
+            var("NOTIFICATION_ARCHIVE_AFTER_DAYS"),
+            var("NOTIFICATION_DELETE_AFTER_DAYS"),
+        )
+    }
+
+    /// Missing or non-positive values fall back to the defaults; deletion never happens
+    /// before archival
+    pub fn new(archive_after_days: Option<i64>, delete_after_days: Option<i64>) -> Self {
+        let defaults = Self::default();
+        let archive_after_days = archive_after_days
+            .filter(|d| *d > 0)
+            .unwrap_or(defaults.archive_after_days);
+        let delete_after_days = delete_after_days
+            .filter(|d| *d > 0)
+            .unwrap_or(defaults.delete_after_days)
+            .max(archive_after_days);
+
+        Self { archive_after_days, delete_after_days }
+    }
+}
+
+// ============================================================================
+// SCHEDULER JOBS
+// ============================================================================
+
+/// Check types the alert scheduler runs, each on its own interval and lease
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertJobType {
+    ExpiryCheck,
+    LowStockCheck,
+    WatchlistCheck,
+    WatchlistTriggerCheck,
+    DigestDelivery,
+    AnnouncementDelivery,
+    NotificationRetention,
+    DataPurge,
+    LicenseExpiryCheck,
+    SanctionsListSync,
+    AuditChainAnchor,
+    VerificationAutoReject,
+}
+
+impl AlertJobType {
+    pub const ALL: [AlertJobType; 12] = [
+        AlertJobType::ExpiryCheck,
+        AlertJobType::LowStockCheck,
+        AlertJobType::WatchlistCheck,
+        AlertJobType::WatchlistTriggerCheck,
+        AlertJobType::DigestDelivery,
+        AlertJobType::AnnouncementDelivery,
+        AlertJobType::NotificationRetention,
+        AlertJobType::DataPurge,
+        AlertJobType::LicenseExpiryCheck,
+        AlertJobType::SanctionsListSync,
+        AlertJobType::AuditChainAnchor,
+        AlertJobType::VerificationAutoReject,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertJobType::ExpiryCheck => "expiry_check",
+            AlertJobType::LowStockCheck => "low_stock_check",
+            AlertJobType::WatchlistCheck => "watchlist_check",
+            AlertJobType::WatchlistTriggerCheck => "watchlist_trigger_check",
+            AlertJobType::DigestDelivery => "digest_delivery",
+            AlertJobType::AnnouncementDelivery => "announcement_delivery",
+            AlertJobType::NotificationRetention => "notification_retention",
+            AlertJobType::DataPurge => "data_purge",
+            AlertJobType::LicenseExpiryCheck => "license_expiry_check",
+            AlertJobType::SanctionsListSync => "sanctions_list_sync",
+            AlertJobType::AuditChainAnchor => "audit_chain_anchor",
+            AlertJobType::VerificationAutoReject => "verification_auto_reject",
+        }
+    }
+}
+
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct AlertSchedulerJob {
+    pub job_type: String,
+    pub interval_seconds: i32,
+    pub lease_seconds: i32,
+    pub is_enabled: bool,
+    pub next_run_at: DateTime<Utc>,
+    pub lease_owner: Option<String>,
+    pub lease_expires_at: Option<DateTime<Utc>>,
+    pub last_started_at: Option<DateTime<Utc>>,
+    pub last_completed_at: Option<DateTime<Utc>>,
+    pub last_status: Option<String>,
+    pub last_error: Option<String>,
+    pub run_count: i32,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Next run of a job that was due at `scheduled`: the first slot of its cadence after
+/// `now`, along with the number of slots missed in between (downtime, long runs). Missed
+/// slots are not replayed; the run that just happened covers them.
+pub fn next_job_run(scheduled: DateTime<Utc>, now: DateTime<Utc>, interval_seconds: i32) -> (DateTime<Utc>, i64) {
+    let interval = interval_seconds.max(1) as i64;
+    let elapsed = (now - scheduled).num_seconds();
+    if elapsed < 0 {
+        return (scheduled + Duration::seconds(interval), 0);
+    }
+
+    let slots = elapsed / interval + 1;
+    (scheduled + Duration::seconds(slots * interval), slots - 1)
 }
 
 // ============================================================================
@@ -481,4 +1571,205 @@ mod tests {
         assert_eq!(payload.severity, AlertSeverity::Critical);
         assert!(payload.title.contains("expires in 5 days"));
     }
+
+    fn local(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        // 2026-06-01 is a Monday
+        chrono::NaiveDate::from_ymd_opt(2026, 6, day).unwrap().and_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn preferences(digest_frequency: &str, quiet_hours_enabled: bool) -> UserAlertPreferences {
+        UserAlertPreferences {
+            user_id: Uuid::new_v4(),
+            expiry_alerts_enabled: true,
+            expiry_alert_days: 30,
+            low_stock_alerts_enabled: true,
+            low_stock_threshold: 10,
+            watchlist_alerts_enabled: true,
+            email_notifications_enabled: false,
+            in_app_notifications_enabled: true,
+            channel_routes: serde_json::json!({}),
+            digest_frequency: digest_frequency.to_string(),
+            digest_hour: 8,
+            digest_weekday: 1,
+            quiet_hours_enabled,
+            quiet_hours_start: 22,
+            quiet_hours_end: 7,
+            timezone: "UTC".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_quiet_hours_window() {
+        assert!(in_quiet_hours(23, 22, 7));
+        assert!(in_quiet_hours(3, 22, 7));
+        assert!(!in_quiet_hours(7, 22, 7));
+        assert!(in_quiet_hours(13, 12, 14));
+        assert!(!in_quiet_hours(14, 12, 14));
+        assert!(!in_quiet_hours(5, 9, 9));
+
+        assert_eq!(quiet_hours_end_after(local(1, 23, 30), 22, 7), local(2, 7, 0));
+        assert_eq!(quiet_hours_end_after(local(2, 3, 0), 22, 7), local(2, 7, 0));
+        assert_eq!(quiet_hours_end_after(local(2, 12, 0), 22, 7), local(2, 12, 0));
+    }
+
+    #[test]
+    fn test_next_digest_time() {
+        assert_eq!(next_digest_at(local(1, 6, 0), DigestFrequency::Daily, 8, 1), Some(local(1, 8, 0)));
+        assert_eq!(next_digest_at(local(1, 8, 0), DigestFrequency::Daily, 8, 1), Some(local(2, 8, 0)));
+        // Wednesday -> next Monday
+        assert_eq!(next_digest_at(local(3, 12, 0), DigestFrequency::Weekly, 8, 1), Some(local(8, 8, 0)));
+        // Monday after the digest hour -> the Monday after
+        assert_eq!(next_digest_at(local(1, 9, 0), DigestFrequency::Weekly, 8, 1), Some(local(8, 8, 0)));
+        assert_eq!(next_digest_at(local(1, 9, 0), DigestFrequency::Off, 8, 1), None);
+    }
+
+    #[test]
+    fn test_hold_until() {
+        let now = local(1, 23, 0);
+        let info = AlertSeverity::Info;
+
+        assert_eq!(hold_until(now, &preferences("off", false), &info), None);
+        assert_eq!(hold_until(now, &preferences("off", true), &info), Some(local(2, 7, 0)));
+        assert_eq!(hold_until(local(1, 12, 0), &preferences("off", true), &info), None);
+        assert_eq!(hold_until(now, &preferences("daily", false), &info), Some(local(2, 8, 0)));
+
+        // A digest hour inside quiet hours moves to their end
+        let mut early = preferences("daily", true);
+        early.digest_hour = 6;
+        assert_eq!(hold_until(now, &early, &info), Some(local(2, 7, 0)));
+
+        // Critical always breaks through
+        assert_eq!(hold_until(now, &preferences("weekly", true), &AlertSeverity::Critical), None);
+    }
+
+    fn trigger(trigger_type: &str, last_best_price: Option<Decimal>, last_in_stock: Option<bool>) -> WatchlistTrigger {
+        WatchlistTrigger {
+            id: Uuid::new_v4(),
+            watchlist_id: Uuid::new_v4(),
+            trigger_type: trigger_type.to_string(),
+            ndc_code: "0093-4155-73".to_string(),
+            price_threshold: Some(Decimal::new(1000, 2)),
+            cooldown_hours: 24,
+            is_active: true,
+            last_best_price,
+            last_in_stock,
+            last_evaluated_at: None,
+            last_triggered_at: None,
+            trigger_count: 0,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn market(best_price: Option<i64>, listing_count: i64) -> MarketSnapshot {
+        MarketSnapshot {
+            best_price: best_price.map(|cents| Decimal::new(cents, 2)),
+            listing_count,
+        }
+    }
+
+    #[test]
+    fn test_price_drop_trigger() {
+        let now = Utc::now();
+        let price = |cents| Some(Decimal::new(cents, 2));
+
+        // Crossing the threshold, or already below it when first checked
+        assert_eq!(evaluate_watchlist_trigger(&trigger("price_drop", price(1200), None), &market(Some(950), 3), now), TriggerEvaluation::Fire);
+        assert_eq!(evaluate_watchlist_trigger(&trigger("price_drop", None, None), &market(Some(1000), 1), now), TriggerEvaluation::Fire);
+        // Still below, no further drop
+        assert_eq!(evaluate_watchlist_trigger(&trigger("price_drop", price(950), None), &market(Some(950), 3), now), TriggerEvaluation::Idle);
+        // Further drop
+        assert_eq!(evaluate_watchlist_trigger(&trigger("price_drop", price(950), None), &market(Some(900), 3), now), TriggerEvaluation::Fire);
+        // Above threshold / no listings
+        assert_eq!(evaluate_watchlist_trigger(&trigger("price_drop", price(1200), None), &market(Some(1100), 3), now), TriggerEvaluation::Idle);
+        assert_eq!(evaluate_watchlist_trigger(&trigger("price_drop", price(1200), None), &market(None, 0), now), TriggerEvaluation::Idle);
+    }
+
+    #[test]
+    fn test_back_in_stock_trigger_and_cooldown() {
+        let now = Utc::now();
+
+        assert_eq!(evaluate_watchlist_trigger(&trigger("back_in_stock", None, Some(false)), &market(Some(500), 2), now), TriggerEvaluation::Fire);
+        // First check only records the state
+        assert_eq!(evaluate_watchlist_trigger(&trigger("back_in_stock", None, None), &market(Some(500), 2), now), TriggerEvaluation::Idle);
+        assert_eq!(evaluate_watchlist_trigger(&trigger("back_in_stock", None, Some(true)), &market(Some(500), 2), now), TriggerEvaluation::Idle);
+
+        let mut recent = trigger("back_in_stock", None, Some(false));
+        recent.last_triggered_at = Some(now - Duration::hours(2));
+        assert_eq!(evaluate_watchlist_trigger(&recent, &market(Some(500), 2), now), TriggerEvaluation::CoolingDown);
+        recent.last_triggered_at = Some(now - Duration::hours(25));
+        assert_eq!(evaluate_watchlist_trigger(&recent, &market(Some(500), 2), now), TriggerEvaluation::Fire);
+    }
+
+    #[test]
+    fn test_retention_policy() {
+        assert_eq!(RetentionPolicy::new(None, None), RetentionPolicy::default());
+        assert_eq!(
+            RetentionPolicy::new(Some(30), Some(180)),
+            RetentionPolicy { archive_after_days: 30, delete_after_days: 180 }
+        );
+        // Never delete before archiving; ignore nonsense values
+        assert_eq!(
+            RetentionPolicy::new(Some(400), None),
+            RetentionPolicy { archive_after_days: 400, delete_after_days: 400 }
+        );
+        assert_eq!(RetentionPolicy::new(Some(0), Some(-5)), RetentionPolicy::default());
+    }
+
+    #[test]
+    fn test_next_job_run() {
+        let scheduled = local(1, 10, 0).and_utc();
+
+        // On time: next slot one interval later
+        let (next, missed) = next_job_run(scheduled, scheduled + Duration::seconds(20), 3600);
+        assert_eq!(next, local(1, 11, 0).and_utc());
+        assert_eq!(missed, 0);
+
+        // After 5.5 hours of downtime: one catch-up run now, cadence kept
+        let (next, missed) = next_job_run(scheduled, scheduled + Duration::minutes(330), 3600);
+        assert_eq!(next, local(1, 16, 0).and_utc());
+        assert_eq!(missed, 5);
+
+        // Exactly on a later slot
+        let (next, missed) = next_job_run(scheduled, scheduled + Duration::hours(2), 3600);
+        assert_eq!(next, local(1, 13, 0).and_utc());
+        assert_eq!(missed, 2);
+    }
+
+    #[test]
+    fn test_digest_payload() {
+        let notification = |alert_type: &str, severity: &str| AlertNotification {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            alert_type: alert_type.to_string(),
+            severity: severity.to_string(),
+            title: format!("{} alert", alert_type),
+            message: "message".to_string(),
+            inventory_id: None,
+            related_user_id: None,
+            metadata: None,
+            action_url: None,
+            is_read: false,
+            is_dismissed: false,
+            created_at: Utc::now(),
+            read_at: None,
+            dismissed_at: None,
+            held_until: None,
+            digest_id: None,
+            archived_at: None,
+        };
+        let held = vec![
+            notification("new_inquiry", "info"),
+            notification("low_stock", "warning"),
+            notification("low_stock", "warning"),
+        ];
+
+        let payload = AlertPayload::new_digest(Uuid::new_v4(), "Daily digest", &held);
+        assert_eq!(payload.alert_type, AlertType::Digest);
+        assert_eq!(payload.severity, AlertSeverity::Warning);
+        assert_eq!(payload.title, "Daily digest: 3 notifications");
+        assert_eq!(payload.message, "2 low stock, 1 new inquiry");
+    }
 }
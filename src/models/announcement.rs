@@ -0,0 +1,191 @@
+/// Platform announcement models: admin-published notices targeted by role and country
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::alerts::{AlertPayload, AlertSeverity, AlertType};
+use crate::models::user::UserRole;
+
+/// Announcements returned by one feed request
+pub const MAX_FEED_ITEMS: i64 = 50;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AnnouncementCategory {
+    General,
+    Maintenance,
+    Regulatory,
+    Product,
+}
+
+impl AnnouncementCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AnnouncementCategory::General => "general",
+            AnnouncementCategory::Maintenance => "maintenance",
+            AnnouncementCategory::Regulatory => "regulatory",
+            AnnouncementCategory::Product => "product",
+        }
+    }
+}
+
+// ============================================================================
+// DATABASE MODELS
+// ============================================================================
+
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct Announcement {
+    pub id: Uuid,
+    pub title: String,
+    pub message: String,
+    pub category: String,
+    pub severity: String,
+    pub link_url: Option<String>,
+    pub target_roles: Vec<String>,
+    pub target_countries: Vec<String>,
+    pub publish_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub published_at: Option<DateTime<Utc>>,
+    pub withdrawn_at: Option<DateTime<Utc>>,
+    pub recipient_count: i32,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Announcement {
+    /// Notification delivered to each recipient when the announcement is published
+    pub fn notification_payload(&self, user_id: Uuid) -> AlertPayload {
+        let severity = match self.severity.as_str() {
+            "critical" => AlertSeverity::Critical,
+            "warning" => AlertSeverity::Warning,
+            _ => AlertSeverity::Info,
+        };
+
+        AlertPayload {
+            user_id,
+            alert_type: AlertType::Announcement,
+            severity,
+            title: self.title.clone(),
+            message: self.message.clone(),
+            inventory_id: None,
+            related_user_id: None,
+            metadata: Some(serde_json::json!({
+                "announcement_id": self.id,
+                "category": self.category,
+                "link_url": self.link_url,
+                "expires_at": self.expires_at,
+            })),
+            action_url: Some(format!("/dashboard/announcements?id={}", self.id)),
+        }
+    }
+}
+
+/// Admin view with read tracking
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct AnnouncementWithStats {
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub announcement: Announcement,
+    pub read_count: i64,
+}
+
+/// Announcement as shown in a user's feed
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct AnnouncementFeedItem {
+    pub id: Uuid,
+    pub title: String,
+    pub message: String,
+    pub category: String,
+    pub severity: String,
+    pub link_url: Option<String>,
+    pub published_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub is_read: bool,
+    pub read_at: Option<DateTime<Utc>>,
+}
+
+// ============================================================================
+// REQUEST / RESPONSE MODELS
+// ============================================================================
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct CreateAnnouncementRequest {
+    #[validate(length(min = 1, max = 200, message = "Title must be 1-200 characters"))]
+    pub title: String,
+    #[validate(length(min = 1, max = 5000, message = "Message must be 1-5000 characters"))]
+    pub message: String,
+    pub category: Option<AnnouncementCategory>,
+    pub severity: Option<AlertSeverity>,
+    #[validate(url(message = "Invalid link URL"))]
+    pub link_url: Option<String>,
+    /// Roles to reach; empty or missing = all roles
+    #[serde(default)]
+    pub target_roles: Vec<UserRole>,
+    /// ISO 3166-1 alpha-2 country codes to reach; empty or missing = all countries.
+    /// Accounts without a country only receive untargeted announcements.
+    #[serde(default)]
+    pub target_countries: Vec<String>,
+    /// Publish later (maintenance windows); default now
+    pub publish_at: Option<DateTime<Utc>>,
+    /// Hide from the feed after this time
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListAnnouncementsQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AnnouncementFeedQuery {
+    /// Only announcements published after this time (for polling)
+    pub since: Option<DateTime<Utc>>,
+    pub unread_only: Option<bool>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AnnouncementFeed {
+    pub announcements: Vec<AnnouncementFeedItem>,
+    pub unread_count: i64,
+    /// Pass as `since` on the next poll
+    pub server_time: DateTime<Utc>,
+}
+
+/// Upper-case and check ISO 3166-1 alpha-2 codes, dropping duplicates
+pub fn normalize_country_codes(codes: &[String]) -> Result<Vec<String>, String> {
+    let mut normalized: Vec<String> = Vec::with_capacity(codes.len());
+    for code in codes {
+        let code = code.trim().to_ascii_uppercase();
+        if code.len() != 2 || !code.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(format!("Invalid country code '{}'", code));
+        }
+        if !normalized.contains(&code) {
+            normalized.push(code);
+        }
+    }
+    Ok(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_country_codes() {
+        let codes = vec!["us".to_string(), " DE".to_string(), "US".to_string()];
+        assert_eq!(normalize_country_codes(&codes).unwrap(), vec!["US", "DE"]);
+
+        assert!(normalize_country_codes(&["USA".to_string()]).is_err());
+        assert!(normalize_country_codes(&["1A".to_string()]).is_err());
+        assert!(normalize_country_codes(&[]).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,335 @@
+/// Inventory and transaction anomaly detection models
+///
+/// The detection rules are plain functions so the thresholds can be tested without a
+/// database; `AnomalyDetectionService` feeds them marketplace data on a schedule.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use sha2::{Digest, Sha256};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+pub const ANOMALY_TYPES: [&str; 4] = [
+    "quantity_jump",
+    "listing_price_outlier",
+    "transaction_price_outlier",
+    "duplicate_listing",
+];
+pub const ANOMALY_SEVERITIES: [&str; 4] = ["low", "medium", "high", "critical"];
+pub const ANOMALY_STATUSES: [&str; 3] = ["open", "confirmed", "dismissed"];
+
+/// Scale factor turning the median absolute deviation into a standard-deviation estimate
+const MAD_SCALE: f64 = 1.4826;
+
+// ============================================================================
+// Thresholds
+// ============================================================================
+
+#[derive(Debug, Clone)]
+pub struct AnomalyThresholds {
+    /// Quantity must grow by at least this factor in one change to be flagged
+    pub quantity_jump_factor: f64,
+    /// ...and by at least this many units
+    pub min_quantity_increase: i64,
+    /// Robust z-score beyond which a price is an outlier
+    pub price_z_score: f64,
+    /// A price must also be this many times above or below the market median
+    pub price_ratio: f64,
+    /// Market prices needed before a price can be judged
+    pub min_price_history: usize,
+    /// Market history window (days)
+    pub history_days: i64,
+    /// Records created or changed within this window (hours) are checked on each run
+    pub detection_window_hours: i64,
+}
+
+impl Default for AnomalyThresholds {
+    fn default() -> Self {
+        Self {
+            quantity_jump_factor: 5.0,
+            min_quantity_increase: 100,
+            price_z_score: 3.5,
+            price_ratio: 2.0,
+            min_price_history: 5,
+            history_days: 180,
+            detection_window_hours: 48,
+        }
+    }
+}
+
+impl AnomalyThresholds {
+    /// Defaults, overridable through ANOMALY_QUANTITY_JUMP_FACTOR, ANOMALY_MIN_QUANTITY_INCREASE,
+    /// ANOMALY_PRICE_Z_SCORE, ANOMALY_PRICE_RATIO, ANOMALY_MIN_PRICE_HISTORY,
+    /// ANOMALY_HISTORY_DAYS and ANOMALY_DETECTION_WINDOW_HOURS
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let env = |name: &str| std::env::var(name).ok().and_then(|v| v.parse::<f64>().ok());
+
+        Self {
+            quantity_jump_factor: env("ANOMALY_QUANTITY_JUMP_FACTOR").unwrap_or(defaults.quantity_jump_factor),
+            min_quantity_increase: env("ANOMALY_MIN_QUANTITY_INCREASE")
+                .map(|v| v as i64)
+                .unwrap_or(defaults.min_quantity_increase),
+            price_z_score: env("ANOMALY_PRICE_Z_SCORE").unwrap_or(defaults.price_z_score),
+            price_ratio: env("ANOMALY_PRICE_RATIO").unwrap_or(defaults.price_ratio),
+            min_price_history: env("ANOMALY_MIN_PRICE_HISTORY")
+                .map(|v| v as usize)
+                .unwrap_or(defaults.min_price_history),
+            history_days: env("ANOMALY_HISTORY_DAYS")
+                .map(|v| v as i64)
+                .unwrap_or(defaults.history_days),
+            detection_window_hours: env("ANOMALY_DETECTION_WINDOW_HOURS")
+                .map(|v| v as i64)
+                .unwrap_or(defaults.detection_window_hours),
+        }
+    }
+}
+
+// ============================================================================
+// Database Models
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct Anomaly {
+    pub id: Uuid,
+    pub anomaly_type: String,
+    pub severity: String,
+    pub status: String,
+    pub entity_type: String,
+    pub entity_id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub pharmaceutical_id: Option<Uuid>,
+    pub score: f64,
+    pub description: String,
+    pub details: serde_json::Value,
+    #[serde(skip_serializing)]
+    pub fingerprint: String,
+    pub reviewed_by: Option<Uuid>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub review_notes: Option<String>,
+    pub detected_at: DateTime<Utc>,
+}
+
+/// A finding produced by a detection rule, before it is stored
+#[derive(Debug, Clone)]
+pub struct DetectedAnomaly {
+    pub anomaly_type: &'static str,
+    pub severity: &'static str,
+    pub entity_type: &'static str,
+    pub entity_id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub pharmaceutical_id: Option<Uuid>,
+    pub score: f64,
+    pub description: String,
+    pub details: serde_json::Value,
+    pub fingerprint: String,
+}
+
+// ============================================================================
+// API Request/Response Models
+// ============================================================================
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListAnomaliesQuery {
+    /// Defaults to open findings; `all` lists every status
+    pub status: Option<String>,
+    pub severity: Option<String>,
+    pub anomaly_type: Option<String>,
+    pub user_id: Option<Uuid>,
+    pub page: Option<i64>,
+    pub page_size: Option<i64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AnomalyListResponse {
+    pub anomalies: Vec<Anomaly>,
+    pub total: i64,
+    pub page: i64,
+    pub page_size: i64,
+    /// Open findings per severity, independent of the filters
+    pub open_by_severity: serde_json::Value,
+}
+
+#[derive(Debug, Default, Deserialize, Validate, ToSchema)]
+pub struct ReviewAnomalyRequest {
+    #[validate(length(max = 2000))]
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, ToSchema)]
+pub struct AnomalyDetectionStats {
+    pub quantity_jumps: i32,
+    pub listing_price_outliers: i32,
+    pub transaction_price_outliers: i32,
+    pub duplicate_listings: i32,
+    pub total_new: i32,
+    pub errors_encountered: i32,
+}
+
+// ============================================================================
+// Detection Rules
+// ============================================================================
+
+fn median(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let mid = sorted.len() / 2;
+    Some(if sorted.len() % 2 == 0 {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PriceOutlier {
+    pub market_median: f64,
+    /// How many times the price is above or below the median (always >= 1)
+    pub ratio: f64,
+    /// Robust z-score (median/MAD); infinite when every market price is identical
+    pub z_score: f64,
+    pub above_market: bool,
+    pub severity: &'static str,
+}
+
+/// Compare a price with market history. A price is an outlier only when it is both far
+/// from the median in robust z-score terms and a sizeable multiple above or below it, so
+/// tight markets do not flag small differences.
+pub fn price_outlier(price: f64, history: &[f64], thresholds: &AnomalyThresholds) -> Option<PriceOutlier> {
+    let history: Vec<f64> = history.iter().copied().filter(|p| *p > 0.0).collect();
+    if price <= 0.0 || history.len() < thresholds.min_price_history {
+        return None;
+    }
+
+    let market_median = median(&history)?;
+    let deviations: Vec<f64> = history.iter().map(|p| (p - market_median).abs()).collect();
+    let mad = median(&deviations)? * MAD_SCALE;
+    let z_score = if mad > 0.0 {
+        (price - market_median).abs() / mad
+    } else if price == market_median {
+        0.0
+    } else {
+        f64::INFINITY
+    };
+
+    let ratio = if price >= market_median { price / market_median } else { market_median / price };
+    if z_score < thresholds.price_z_score || ratio < thresholds.price_ratio {
+        return None;
+    }
+
+    let severity = if ratio >= thresholds.price_ratio * 5.0 {
+        "critical"
+    } else if ratio >= thresholds.price_ratio * 2.5 {
+        "high"
+    } else {
+        "medium"
+    };
+
+    Some(PriceOutlier {
+        market_median,
+        ratio,
+        z_score,
+        above_market: price > market_median,
+        severity,
+    })
+}
+
+/// Severity of a quantity change, or `None` when it is an ordinary restock
+pub fn quantity_jump(old_quantity: i64, new_quantity: i64, thresholds: &AnomalyThresholds) -> Option<(f64, &'static str)> {
+    if old_quantity <= 0 || new_quantity - old_quantity < thresholds.min_quantity_increase {
+        return None;
+    }
+
+    let factor = new_quantity as f64 / old_quantity as f64;
+    if factor < thresholds.quantity_jump_factor {
+        return None;
+    }
+
+    let severity = if factor >= thresholds.quantity_jump_factor * 20.0 {
+        "critical"
+    } else if factor >= thresholds.quantity_jump_factor * 4.0 {
+        "high"
+    } else {
+        "medium"
+    };
+
+    Some((factor, severity))
+}
+
+/// Batch numbers compared case- and punctuation-insensitively ("ab-123 " == "AB123")
+pub fn normalize_batch_number(batch_number: &str) -> String {
+    batch_number
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_uppercase)
+        .collect()
+}
+
+/// Stable key of a finding; `parts` identify what was flagged (order-insensitive)
+pub fn anomaly_fingerprint(anomaly_type: &str, parts: &[String]) -> String {
+    let mut parts = parts.to_vec();
+    parts.sort();
+
+    let mut hasher = Sha256::new();
+    hasher.update(anomaly_type.as_bytes());
+    for part in &parts {
+        hasher.update(b"|");
+        hasher.update(part.as_bytes());
+    }
+    hex::encode(hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_price_outlier() {
+        let thresholds = AnomalyThresholds::default();
+        let history = [10.0, 10.5, 9.8, 11.0, 10.2, 9.9];
+
+        assert!(price_outlier(10.4, &history, &thresholds).is_none());
+
+        let high = price_outlier(60.0, &history, &thresholds).unwrap();
+        assert!(high.above_market);
+        assert_eq!(high.severity, "high");
+
+        let low = price_outlier(1.0, &history, &thresholds).unwrap();
+        assert!(!low.above_market);
+        assert_eq!(low.severity, "critical");
+
+        // Too little history to judge
+        assert!(price_outlier(60.0, &history[..3], &thresholds).is_none());
+        // Identical market prices: only the ratio decides
+        assert!(price_outlier(11.0, &[10.0; 6], &thresholds).is_none());
+        assert!(price_outlier(25.0, &[10.0; 6], &thresholds).is_some());
+    }
+
+    #[test]
+    fn test_quantity_jump() {
+        let thresholds = AnomalyThresholds::default();
+
+        assert!(quantity_jump(100, 300, &thresholds).is_none());
+        assert!(quantity_jump(10, 80, &thresholds).is_none()); // below the minimum increase
+        assert!(quantity_jump(0, 5000, &thresholds).is_none()); // first stock-in
+        assert_eq!(quantity_jump(50, 500, &thresholds).map(|(_, s)| s), Some("medium"));
+        assert_eq!(quantity_jump(20, 1000, &thresholds).map(|(_, s)| s), Some("high"));
+        assert_eq!(quantity_jump(1, 1000, &thresholds).map(|(_, s)| s), Some("critical"));
+    }
+
+    #[test]
+    fn test_batch_normalization_and_fingerprint() {
+        assert_eq!(normalize_batch_number(" ab-123/x "), "AB123X");
+
+        let a = anomaly_fingerprint("duplicate_listing", &["1".to_string(), "2".to_string()]);
+        let b = anomaly_fingerprint("duplicate_listing", &["2".to_string(), "1".to_string()]);
+        assert_eq!(a, b);
+        assert_ne!(a, anomaly_fingerprint("quantity_jump", &["1".to_string(), "2".to_string()]));
+    }
+}
@@ -0,0 +1,363 @@
+/// Audit log integrity chain models: chain links, anchors of the chain head and the
+/// verification walk that detects gaps, broken links and modified entries
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use sqlx::FromRow;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// prev_hash of the first entry of the chain
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Issues listed in a verification report; further issues are only counted
+pub const MAX_REPORTED_ISSUES: usize = 100;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ChainIssueKind {
+    /// Entries missing from the chain without a retention tombstone
+    Gap,
+    /// prev_hash doesn't match the preceding entry's hash
+    BrokenLink,
+    /// Entry content no longer matches its hash
+    Modified,
+    /// The chain ends before the recorded head (tail removed)
+    Truncated,
+    /// An anchored hash doesn't match the entry at its position
+    AnchorMismatch,
+    /// Entries without chain data (inserted with the chain trigger disabled)
+    Unchained,
+}
+
+// ============================================================================
+// DATABASE MODELS
+// ============================================================================
+
+/// One position of the chain: a stored entry, or the tombstone of a deleted one
+#[derive(Debug, Clone, FromRow)]
+pub struct ChainLink {
+    pub chain_seq: i64,
+    pub prev_hash: String,
+    pub entry_hash: String,
+    /// Hash recomputed from the stored content; None for tombstones
+    pub recomputed_hash: Option<String>,
+    pub is_tombstone: bool,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct AuditChainHead {
+    pub chain_seq: i64,
+    pub entry_hash: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct AuditChainAnchor {
+    pub id: Uuid,
+    pub chain_seq: i64,
+    pub entry_hash: String,
+    pub verified_from_seq: Option<i64>,
+    pub issues_found: i32,
+    pub published_at: Option<DateTime<Utc>>,
+    pub publish_error: Option<String>,
+    pub anchored_by: Option<Uuid>,
+    pub anchored_at: DateTime<Utc>,
+}
+
+// ============================================================================
+// VERIFICATION
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, ToSchema)]
+pub struct ChainIssue {
+    pub kind: ChainIssueKind,
+    pub chain_seq: i64,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ChainVerificationReport {
+    pub valid: bool,
+    /// First position checked (1 for a full verification)
+    pub from_seq: i64,
+    pub head_seq: i64,
+    pub entries_checked: i64,
+    pub tombstones_checked: i64,
+    pub anchors_checked: i64,
+    pub issue_count: i64,
+    pub issues: Vec<ChainIssue>,
+    pub verified_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct VerifyAuditChainQuery {
+    /// Start at this anchor instead of the beginning of the chain
+    pub since_anchor: Option<Uuid>,
+}
+
+/// Walks chain links in sequence order. Start at the beginning of the chain with
+/// `new(0, GENESIS_HASH)`, or after an anchor with its position and hash.
+pub struct ChainVerifier {
+    from_seq: i64,
+    last_seq: i64,
+    last_hash: String,
+    anchors: HashMap<i64, String>,
+    entries_checked: i64,
+    tombstones_checked: i64,
+    anchors_checked: i64,
+    issue_count: i64,
+    issues: Vec<ChainIssue>,
+}
+
+impl ChainVerifier {
+    pub fn new(after_seq: i64, after_hash: &str, anchors: HashMap<i64, String>) -> Self {
+        Self {
+            from_seq: after_seq + 1,
+            last_seq: after_seq,
+            last_hash: after_hash.to_string(),
+            anchors,
+            entries_checked: 0,
+            tombstones_checked: 0,
+            anchors_checked: 0,
+            issue_count: 0,
+            issues: Vec::new(),
+        }
+    }
+
+    fn report(&mut self, kind: ChainIssueKind, chain_seq: i64, detail: String) {
+        self.issue_count += 1;
+        if self.issues.len() < MAX_REPORTED_ISSUES {
+            self.issues.push(ChainIssue { kind, chain_seq, detail });
+        }
+    }
+
+    /// Check the next link; links must be passed in ascending chain_seq order
+    pub fn push(&mut self, link: &ChainLink) {
+        if link.chain_seq <= self.last_seq {
+            self.report(
+                ChainIssueKind::BrokenLink,
+                link.chain_seq,
+                "Position occurs twice (entry and tombstone)".to_string(),
+            );
+            return;
+        }
+        if link.chain_seq > self.last_seq + 1 {
+            let detail = if link.chain_seq == self.last_seq + 2 {
+                format!("Entry {} is missing", self.last_seq + 1)
+            } else {
+                format!("Entries {} to {} are missing", self.last_seq + 1, link.chain_seq - 1)
+            };
+            self.report(ChainIssueKind::Gap, self.last_seq + 1, detail);
+        } else if link.prev_hash != self.last_hash {
+            self.report(
+                ChainIssueKind::BrokenLink,
+                link.chain_seq,
+                "Previous hash doesn't match the preceding entry".to_string(),
+            );
+        }
+
+        if link.is_tombstone {
+            self.tombstones_checked += 1;
+        } else {
+            self.entries_checked += 1;
+            if link.recomputed_hash.as_deref() != Some(link.entry_hash.as_str()) {
+                self.report(
+                    ChainIssueKind::Modified,
+                    link.chain_seq,
+                    "Entry content doesn't match its hash".to_string(),
+                );
+            }
+        }
+
+        if let Some(anchored) = self.anchors.get(&link.chain_seq).cloned() {
+            self.anchors_checked += 1;
+            if anchored != link.entry_hash {
+                self.report(
+                    ChainIssueKind::AnchorMismatch,
+                    link.chain_seq,
+                    "Hash differs from the anchored chain head".to_string(),
+                );
+            }
+        }
+
+        self.last_seq = link.chain_seq;
+        self.last_hash = link.entry_hash.clone();
+    }
+
+    /// Compare the end of the chain with the recorded head and the anchors
+    pub fn finish(mut self, head: Option<&AuditChainHead>, unchained_entries: i64) -> ChainVerificationReport {
+        let head_seq = head.map_or(self.last_seq, |h| h.chain_seq);
+
+        if let Some(head) = head {
+            if self.last_seq < head.chain_seq {
+                let detail = format!("Chain ends at {} but the head is at {}", self.last_seq, head.chain_seq);
+                self.report(ChainIssueKind::Truncated, self.last_seq + 1, detail);
+            } else if self.last_seq == head.chain_seq && self.last_hash != head.entry_hash {
+                self.report(
+                    ChainIssueKind::BrokenLink,
+                    head.chain_seq,
+                    "Last entry doesn't match the chain head".to_string(),
+                );
+            }
+        }
+
+        let mut missing_anchors: Vec<i64> = self
+            .anchors
+            .keys()
+            .copied()
+            .filter(|&seq| seq > self.last_seq)
+            .collect();
+        missing_anchors.sort_unstable();
+        for seq in missing_anchors {
+            self.report(
+                ChainIssueKind::AnchorMismatch,
+                seq,
+                "Anchored entry is no longer in the chain".to_string(),
+            );
+        }
+
+        if unchained_entries > 0 {
+            self.report(
+                ChainIssueKind::Unchained,
+                0,
+                format!("{} entries have no chain data", unchained_entries),
+            );
+        }
+
+        ChainVerificationReport {
+            valid: self.issue_count == 0,
+            from_seq: self.from_seq,
+            head_seq,
+            entries_checked: self.entries_checked,
+            tombstones_checked: self.tombstones_checked,
+            anchors_checked: self.anchors_checked,
+            issue_count: self.issue_count,
+            issues: self.issues,
+            verified_at: Utc::now(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Links with synthetic hashes: entry n has hash "hn"
+    fn chain(n: i64) -> Vec<ChainLink> {
+        (1..=n)
+            .map(|seq| ChainLink {
+                chain_seq: seq,
+                prev_hash: if seq == 1 { GENESIS_HASH.to_string() } else { format!("h{}", seq - 1) },
+                entry_hash: format!("h{}", seq),
+                recomputed_hash: Some(format!("h{}", seq)),
+                is_tombstone: false,
+            })
+            .collect()
+    }
+
+    fn head(seq: i64) -> AuditChainHead {
+        AuditChainHead { chain_seq: seq, entry_hash: format!("h{}", seq), updated_at: Utc::now() }
+    }
+
+    fn verify(links: &[ChainLink], head_seq: i64, anchors: &[i64]) -> ChainVerificationReport {
+        let anchors = anchors.iter().map(|&seq| (seq, format!("h{}", seq))).collect();
+        let mut verifier = ChainVerifier::new(0, GENESIS_HASH, anchors);
+        for link in links {
+            verifier.push(link);
+        }
+        verifier.finish(Some(&head(head_seq)), 0)
+    }
+
+    #[test]
+    fn test_intact_chain() {
+        let report = verify(&chain(5), 5, &[3, 5]);
+        assert!(report.valid);
+        assert_eq!(report.entries_checked, 5);
+        assert_eq!(report.anchors_checked, 2);
+    }
+
+    #[test]
+    fn test_modified_entry() {
+        let mut links = chain(5);
+        links[2].recomputed_hash = Some("tampered".to_string());
+
+        let report = verify(&links, 5, &[]);
+        assert!(!report.valid);
+        assert_eq!(report.issues[0].kind, ChainIssueKind::Modified);
+        assert_eq!(report.issues[0].chain_seq, 3);
+    }
+
+    #[test]
+    fn test_deleted_entry_is_a_gap() {
+        let mut links = chain(5);
+        links.remove(2);
+
+        let report = verify(&links, 5, &[]);
+        assert_eq!(report.issue_count, 1);
+        assert_eq!(report.issues[0].kind, ChainIssueKind::Gap);
+        assert_eq!(report.issues[0].detail, "Entry 3 is missing");
+    }
+
+    #[test]
+    fn test_tombstones_keep_chain_valid() {
+        let mut links = chain(5);
+        links[0].is_tombstone = true;
+        links[0].recomputed_hash = None;
+        links[1].is_tombstone = true;
+        links[1].recomputed_hash = None;
+
+        let report = verify(&links, 5, &[]);
+        assert!(report.valid);
+        assert_eq!(report.tombstones_checked, 2);
+        assert_eq!(report.entries_checked, 3);
+    }
+
+    #[test]
+    fn test_rehashed_chain_is_caught_by_anchor() {
+        // Entry 2 rewritten and the chain re-linked from there on
+        let mut links = chain(4);
+        for link in links.iter_mut().skip(1) {
+            link.entry_hash = format!("x{}", link.chain_seq);
+            link.recomputed_hash = Some(link.entry_hash.clone());
+            if link.chain_seq > 2 {
+                link.prev_hash = format!("x{}", link.chain_seq - 1);
+            }
+        }
+        let anchors = HashMap::from([(3, "h3".to_string())]);
+        let mut verifier = ChainVerifier::new(0, GENESIS_HASH, anchors);
+        for link in &links {
+            verifier.push(link);
+        }
+        let report = verifier.finish(None, 0);
+
+        // The re-linked chain is consistent in itself; only the anchor shows the rewrite
+        let kinds: Vec<ChainIssueKind> = report.issues.iter().map(|i| i.kind).collect();
+        assert_eq!(kinds, vec![ChainIssueKind::AnchorMismatch]);
+        assert_eq!(report.issues[0].chain_seq, 3);
+    }
+
+    #[test]
+    fn test_truncated_tail() {
+        let links = chain(3);
+        let report = verify(&links, 5, &[5]);
+
+        let kinds: Vec<ChainIssueKind> = report.issues.iter().map(|i| i.kind).collect();
+        assert_eq!(kinds, vec![ChainIssueKind::Truncated, ChainIssueKind::AnchorMismatch]);
+    }
+
+    #[test]
+    fn test_verification_from_anchor() {
+        let links = chain(6);
+        let mut verifier = ChainVerifier::new(3, "h3", HashMap::new());
+        for link in links.iter().skip(3) {
+            verifier.push(link);
+        }
+        let report = verifier.finish(Some(&head(6)), 0);
+        assert!(report.valid);
+        assert_eq!(report.from_seq, 4);
+        assert_eq!(report.entries_checked, 3);
+    }
+}
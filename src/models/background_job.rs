@@ -0,0 +1,362 @@
+/// Background job models: the persistent job queue, per-type settings and the payloads
+/// of the job types
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::models::{regulator_catalog::RegulatorSource, sanctions::SanctionsSource};
+
+/// First retry delay; it quadruples with every further attempt
+const RETRY_BASE_SECONDS: i64 = 30;
+
+/// Longest delay between two attempts
+const RETRY_MAX_SECONDS: i64 = 3600;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum JobType {
+    /// Manual ERP sync of one connection
+    ErpSync,
+    /// Inventory rows of an AI import session
+    AiImport,
+    /// OpenFDA drug catalog sync, new or resumed from its checkpoint
+    OpenFdaSync,
+    /// Regulatory document batch
+    DocumentBatch,
+    /// Manual sanctions list sync
+    SanctionsListSync,
+    /// Export file of inventory, transactions, audit logs or a catalog
+    DataExport,
+    /// Re-encryption of encrypted columns with the active encryption key
+    EncryptionReencrypt,
+    /// Operations submitted together through the batch API
+    BatchOperation,
+    /// OpenFDA device (UDI/GUDID) catalog sync
+    OpenFdaDeviceSync,
+    /// OpenFDA drug enforcement (recall) sync
+    OpenFdaRecallSync,
+    /// EMA ePI catalog sync
+    EmaSync,
+    /// Regulator catalog sync of one source (Health Canada, MHRA, Swissmedic)
+    RegulatorCatalogSync,
+}
+
+impl JobType {
+    pub const ALL: [JobType; 12] = [
+        JobType::ErpSync,
+        JobType::AiImport,
+        JobType::OpenFdaSync,
+        JobType::DocumentBatch,
+        JobType::SanctionsListSync,
+        JobType::DataExport,
+        JobType::EncryptionReencrypt,
+        JobType::BatchOperation,
+        JobType::OpenFdaDeviceSync,
+        JobType::OpenFdaRecallSync,
+        JobType::EmaSync,
+        JobType::RegulatorCatalogSync,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobType::ErpSync => "erp_sync",
+            JobType::AiImport => "ai_import",
+            JobType::OpenFdaSync => "openfda_sync",
+            JobType::DocumentBatch => "document_batch",
+            JobType::SanctionsListSync => "sanctions_list_sync",
+            JobType::DataExport => "data_export",
+            JobType::EncryptionReencrypt => "encryption_reencrypt",
+            JobType::BatchOperation => "batch_operation",
+            JobType::OpenFdaDeviceSync => "openfda_device_sync",
+            JobType::OpenFdaRecallSync => "openfda_recall_sync",
+            JobType::EmaSync => "ema_sync",
+            JobType::RegulatorCatalogSync => "regulator_catalog_sync",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|job_type| job_type.as_str() == value)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobStatus::Queued => "queued",
+            JobStatus::Running => "running",
+            JobStatus::Succeeded => "succeeded",
+            JobStatus::Failed => "failed",
+            JobStatus::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "queued" => Some(JobStatus::Queued),
+            "running" => Some(JobStatus::Running),
+            "succeeded" => Some(JobStatus::Succeeded),
+            "failed" => Some(JobStatus::Failed),
+            "cancelled" => Some(JobStatus::Cancelled),
+            _ => None,
+        }
+    }
+}
+
+/// Delay before the next attempt of a job that failed `attempts` times
+pub fn job_retry_delay(attempts: i32) -> Duration {
+    let exponent = attempts.saturating_sub(1).clamp(0, 10) as u32;
+    Duration::seconds((RETRY_BASE_SECONDS * 4_i64.pow(exponent)).min(RETRY_MAX_SECONDS))
+}
+
+// ============================================================================
+// DATABASE MODELS
+// ============================================================================
+
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct BackgroundJob {
+    pub id: Uuid,
+    pub job_type: String,
+    pub payload: serde_json::Value,
+    pub status: String,
+    pub dedupe_key: Option<String>,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub run_at: DateTime<Utc>,
+    pub lease_owner: Option<String>,
+    pub lease_expires_at: Option<DateTime<Utc>>,
+    pub cancel_requested: bool,
+    pub last_error: Option<String>,
+    pub result: Option<serde_json::Value>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl BackgroundJob {
+    /// A later attempt after a failure or after the worker running it died
+    pub fn is_retry(&self) -> bool {
+        self.attempts > 1
+    }
+
+    pub fn payload<T: serde::de::DeserializeOwned>(&self) -> serde_json::Result<T> {
+        serde_json::from_value(self.payload.clone())
+    }
+}
+
+/// Concurrency, retry and lease settings of a job type
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct JobTypeSettings {
+    pub job_type: String,
+    /// Jobs of this type running at once across all instances
+    pub max_concurrency: i32,
+    pub max_attempts: i32,
+    pub lease_seconds: i32,
+    pub is_enabled: bool,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A job to enqueue
+#[derive(Debug, Clone)]
+pub struct NewJob {
+    pub job_type: JobType,
+    pub payload: serde_json::Value,
+    pub dedupe_key: Option<String>,
+    pub run_at: Option<DateTime<Utc>>,
+    pub created_by: Option<Uuid>,
+}
+
+impl NewJob {
+    pub fn new(job_type: JobType, payload: &impl Serialize) -> Self {
+        Self {
+            job_type,
+            payload: serde_json::to_value(payload).unwrap_or_default(),
+            dedupe_key: None,
+            run_at: None,
+            created_by: None,
+        }
+    }
+
+    pub fn with_dedupe_key(mut self, key: impl Into<String>) -> Self {
+        self.dedupe_key = Some(key.into());
+        self
+    }
+
+    pub fn created_by(mut self, user_id: Uuid) -> Self {
+        self.created_by = Some(user_id);
+        self
+    }
+
+    /// Run no earlier than `at`
+    pub fn run_at(mut self, at: DateTime<Utc>) -> Self {
+        self.run_at = Some(at);
+        self
+    }
+}
+
+// ============================================================================
+// JOB PAYLOADS
+// ============================================================================
+
+/// The handler claims the connection before enqueueing; a retry claims it again
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErpSyncJob {
+    pub connection_id: Uuid,
+    /// 'atlas_to_erp', 'erp_to_atlas' or 'bidirectional'
+    pub direction: String,
+    pub user_id: Uuid,
+}
+
+/// Imports the session's file with its approved mapping; a retry skips the row batches
+/// an earlier attempt committed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AiImportJob {
+    pub session_id: Uuid,
+    pub user_id: Uuid,
+}
+
+/// Runs the sync of an existing sync log; a retry continues from its resume checkpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenFdaSyncJob {
+    pub sync_log_id: Uuid,
+    /// 'delta' for an incremental sync; anything else is a full sync
+    pub sync_type: String,
+    /// Continue an interrupted sync from its checkpoint instead of starting over
+    #[serde(default)]
+    pub resume: bool,
+}
+
+/// Runs the device sync of an existing sync log; a retry fetches again from the start,
+/// upserting what the earlier attempt already stored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenFdaDeviceSyncJob {
+    pub sync_log_id: Uuid,
+    /// Most records to fetch; None fetches the whole dataset
+    pub limit: Option<usize>,
+}
+
+/// Runs the recall sync of an existing sync log; a retry fetches again from the start
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenFdaRecallSyncJob {
+    pub sync_log_id: Uuid,
+    /// Re-read the whole dataset instead of reports newer than the latest stored one
+    pub full: bool,
+}
+
+/// Runs the EMA sync of an existing sync log; a retry fetches again from the start
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmaSyncJob {
+    pub sync_log_id: Uuid,
+    pub language: String,
+    pub limit: usize,
+    pub sync_type: String,
+}
+
+/// Runs the sync of an existing regulator catalog sync log; a retry downloads the
+/// source again
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegulatorCatalogSyncJob {
+    pub sync_log_id: Uuid,
+    pub source: RegulatorSource,
+}
+
+/// Processes the pending items of a batch, so a retry continues where it stopped
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentBatchJob {
+    pub batch_id: Uuid,
+}
+
+/// Runs the pending operations of a batch, so a retry continues where it stopped
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchOperationJob {
+    pub batch_id: Uuid,
+}
+
+/// Writes the export file; a retry starts the file over
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataExportJob {
+    pub export_id: Uuid,
+}
+
+/// Re-encrypts what isn't on the active key yet, so a retry continues with the rows left
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptionReencryptJob {
+    /// Key version of the rotation that queued the job; data moves to whichever key is
+    /// active when it runs
+    pub key_version: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SanctionsListSyncJob {
+    /// None syncs every configured list
+    pub source: Option<SanctionsSource>,
+    pub requested_by: Uuid,
+}
+
+// ============================================================================
+// REQUEST/RESPONSE MODELS
+// ============================================================================
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct BackgroundJobQuery {
+    pub job_type: Option<String>,
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// Optional body of a retry; without `run_at` the job is queued right away
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct RetryBackgroundJobRequest {
+    pub run_at: Option<DateTime<Utc>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_job_retry_delay() {
+        assert_eq!(job_retry_delay(1), Duration::seconds(30));
+        assert_eq!(job_retry_delay(2), Duration::seconds(120));
+        assert_eq!(job_retry_delay(3), Duration::seconds(480));
+        assert_eq!(job_retry_delay(5), Duration::seconds(3600));
+        assert_eq!(job_retry_delay(40), Duration::seconds(3600));
+        assert_eq!(job_retry_delay(0), Duration::seconds(30));
+    }
+
+    #[test]
+    fn test_job_type_round_trip() {
+        for job_type in JobType::ALL {
+            assert_eq!(JobType::parse(job_type.as_str()), Some(job_type));
+        }
+        assert_eq!(JobType::parse("expiry_check"), None);
+        assert_eq!(JobStatus::parse("cancelled"), Some(JobStatus::Cancelled));
+        assert_eq!(JobStatus::parse("done"), None);
+    }
+
+    #[test]
+    fn test_openfda_payload_defaults_to_new_sync() {
+        let job: OpenFdaSyncJob = serde_json::from_value(serde_json::json!({
+            "sync_log_id": Uuid::nil(),
+            "sync_type": "delta",
+        }))
+        .unwrap();
+        assert!(!job.resume);
+    }
+}
@@ -0,0 +1,238 @@
+/// Batch operation models: up to MAX_BATCH_OPERATIONS operations of one kind submitted
+/// together, run by a background job with a result per operation
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::models::{inventory::UpdateInventoryRequest, marketplace::CreateInquiryRequest};
+
+/// Operations a single batch may contain
+pub const MAX_BATCH_OPERATIONS: usize = 100;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchOperationKind {
+    CreateInquiries,
+    UpdateInventory,
+    DismissNotifications,
+}
+
+impl BatchOperationKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BatchOperationKind::CreateInquiries => "create_inquiries",
+            BatchOperationKind::UpdateInventory => "update_inventory",
+            BatchOperationKind::DismissNotifications => "dismiss_notifications",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "create_inquiries" => Some(BatchOperationKind::CreateInquiries),
+            "update_inventory" => Some(BatchOperationKind::UpdateInventory),
+            "dismiss_notifications" => Some(BatchOperationKind::DismissNotifications),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchOperationStatus {
+    Queued,
+    Running,
+    Completed,
+    CompletedWithErrors,
+    Failed,
+}
+
+impl BatchOperationStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BatchOperationStatus::Queued => "queued",
+            BatchOperationStatus::Running => "running",
+            BatchOperationStatus::Completed => "completed",
+            BatchOperationStatus::CompletedWithErrors => "completed_with_errors",
+            BatchOperationStatus::Failed => "failed",
+        }
+    }
+
+    /// Final status of a batch whose items have all been processed
+    pub fn finished(succeeded: i32, failed: i32) -> Self {
+        match (succeeded, failed) {
+            (_, 0) => BatchOperationStatus::Completed,
+            (0, _) => BatchOperationStatus::Failed,
+            _ => BatchOperationStatus::CompletedWithErrors,
+        }
+    }
+}
+
+// ============================================================================
+// DATABASE MODELS
+// ============================================================================
+
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct BatchOperation {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub operation: String,
+    pub status: String,
+    pub total_items: i32,
+    pub processed_items: i32,
+    pub succeeded_items: i32,
+    pub failed_items: i32,
+    pub error: Option<String>,
+    /// Background job running the batch
+    pub job_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl BatchOperation {
+    pub fn is_finished(&self) -> bool {
+        !matches!(self.status.as_str(), "queued" | "running")
+    }
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct BatchOperationItem {
+    pub id: Uuid,
+    pub batch_id: Uuid,
+    /// Index of the operation in the submitted list
+    pub position: i32,
+    pub input: serde_json::Value,
+    /// 'pending', 'succeeded' or 'failed'
+    pub status: String,
+    /// The created inquiry, the updated inventory item or the dismissed notification id
+    pub result: Option<serde_json::Value>,
+    pub error: Option<String>,
+    /// Problem code of the error, e.g. 'not_found' or 'conflict'
+    pub error_code: Option<String>,
+    pub processed_at: Option<DateTime<Utc>>,
+}
+
+// ============================================================================
+// REQUEST/RESPONSE MODELS
+// ============================================================================
+
+/// Changes to one of the user's inventory items
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct InventoryUpdateOperation {
+    pub id: Uuid,
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub changes: UpdateInventoryRequest,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct NotificationDismissOperation {
+    pub id: Uuid,
+}
+
+/// Operations of one kind, in the order they run
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "operation", content = "items", rename_all = "snake_case")]
+pub enum CreateBatchOperationRequest {
+    CreateInquiries(Vec<CreateInquiryRequest>),
+    UpdateInventory(Vec<InventoryUpdateOperation>),
+    DismissNotifications(Vec<NotificationDismissOperation>),
+}
+
+impl CreateBatchOperationRequest {
+    pub fn kind(&self) -> BatchOperationKind {
+        match self {
+            CreateBatchOperationRequest::CreateInquiries(_) => BatchOperationKind::CreateInquiries,
+            CreateBatchOperationRequest::UpdateInventory(_) => BatchOperationKind::UpdateInventory,
+            CreateBatchOperationRequest::DismissNotifications(_) => BatchOperationKind::DismissNotifications,
+        }
+    }
+
+    /// Validate the size of the batch and every operation in it; returns the operations
+    /// as they are stored
+    pub fn check_items(&self) -> Result<Vec<serde_json::Value>, String> {
+        fn check<T: Serialize + Validate>(items: &[T]) -> Result<Vec<serde_json::Value>, String> {
+            if items.is_empty() || items.len() > MAX_BATCH_OPERATIONS {
+                return Err(format!("Between 1 and {} operations per batch", MAX_BATCH_OPERATIONS));
+            }
+            items
+                .iter()
+                .enumerate()
+                .map(|(position, item)| {
+                    item.validate()
+                        .map_err(|e| format!("Operation {}: {}", position, e.to_string().replace('\n', "; ")))?;
+                    serde_json::to_value(item).map_err(|e| format!("Operation {}: {}", position, e))
+                })
+                .collect()
+        }
+
+        match self {
+            CreateBatchOperationRequest::CreateInquiries(items) => check(items),
+            CreateBatchOperationRequest::UpdateInventory(items) => check(items),
+            CreateBatchOperationRequest::DismissNotifications(items) => check(items),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct BatchOperationResultsQuery {
+    /// Only items with this status ('pending', 'succeeded' or 'failed')
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BatchOperationResults {
+    pub batch: BatchOperation,
+    pub items: Vec<BatchOperationItem>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_finished_status() {
+        assert_eq!(BatchOperationStatus::finished(5, 0), BatchOperationStatus::Completed);
+        assert_eq!(BatchOperationStatus::finished(3, 2), BatchOperationStatus::CompletedWithErrors);
+        assert_eq!(BatchOperationStatus::finished(0, 4), BatchOperationStatus::Failed);
+    }
+
+    #[test]
+    fn test_request_is_tagged_by_operation() {
+        let request: CreateBatchOperationRequest = serde_json::from_value(serde_json::json!({
+            "operation": "update_inventory",
+            "items": [{ "id": Uuid::nil(), "quantity": 40, "status": "available" }],
+        }))
+        .unwrap();
+        assert_eq!(request.kind(), BatchOperationKind::UpdateInventory);
+        assert_eq!(BatchOperationKind::parse(request.kind().as_str()), Some(request.kind()));
+
+        let items = request.check_items().unwrap();
+        assert_eq!(items[0]["id"], serde_json::json!(Uuid::nil()));
+        assert_eq!(items[0]["quantity"], 40);
+    }
+
+    #[test]
+    fn test_check_items() {
+        let empty = CreateBatchOperationRequest::DismissNotifications(Vec::new());
+        assert!(empty.check_items().is_err());
+
+        let too_many = CreateBatchOperationRequest::DismissNotifications(
+            (0..=MAX_BATCH_OPERATIONS).map(|_| NotificationDismissOperation { id: Uuid::nil() }).collect(),
+        );
+        assert!(too_many.check_items().is_err());
+
+        let invalid = CreateBatchOperationRequest::CreateInquiries(vec![
+            CreateInquiryRequest { inventory_id: Uuid::nil(), quantity_requested: 5, message: None },
+            CreateInquiryRequest { inventory_id: Uuid::nil(), quantity_requested: 0, message: None },
+        ]);
+        let error = invalid.check_items().unwrap_err();
+        assert!(error.starts_with("Operation 1:"), "{}", error);
+    }
+}
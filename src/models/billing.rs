@@ -0,0 +1,295 @@
+/// Usage metering and billing: meters, plans, per-account usage and monthly statements
+
+use chrono::{DateTime, Datelike, Months, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+// ============================================================================
+// Meters
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BillingMeter {
+    /// Available inventory listed on the marketplace (gauge)
+    Listings,
+    /// Marketplace transactions, billed to the seller
+    Transactions,
+    /// Claude API calls made for the account
+    AiCalls,
+    /// ERP connections that are not disabled (gauge)
+    ErpConnections,
+    /// Authenticated API requests
+    ApiCalls,
+}
+
+impl BillingMeter {
+    pub const ALL: [BillingMeter; 5] = [
+        BillingMeter::Listings,
+        BillingMeter::Transactions,
+        BillingMeter::AiCalls,
+        BillingMeter::ErpConnections,
+        BillingMeter::ApiCalls,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BillingMeter::Listings => "listings",
+            BillingMeter::Transactions => "transactions",
+            BillingMeter::AiCalls => "ai_calls",
+            BillingMeter::ErpConnections => "erp_connections",
+            BillingMeter::ApiCalls => "api_calls",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|meter| meter.as_str() == value)
+    }
+
+    /// Gauges bill their peak over the period; counters bill their sum
+    pub fn is_gauge(&self) -> bool {
+        matches!(self, BillingMeter::Listings | BillingMeter::ErpConnections)
+    }
+}
+
+// ============================================================================
+// Billing Periods
+// ============================================================================
+
+/// Calendar month containing `date`: first day and first day of the next month
+pub fn billing_period(date: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let start = date.with_day(1).unwrap_or(date);
+    (start, start + Months::new(1))
+}
+
+/// Parse a `YYYY-MM` period into its first day
+pub fn parse_billing_month(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(&format!("{}-01", value.trim()), "%Y-%m-%d").ok()
+}
+
+// ============================================================================
+// Database Models
+// ============================================================================
+
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct BillingPlan {
+    pub plan: String,
+    pub display_name: String,
+    pub monthly_base_fee_usd: Decimal,
+    pub is_active: bool,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct BillingPlanMeter {
+    pub plan: String,
+    pub meter: String,
+    pub included_quantity: i64,
+    pub overage_unit_price_usd: Option<Decimal>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct BillingPlanDetails {
+    #[serde(flatten)]
+    pub plan: BillingPlan,
+    pub meters: Vec<BillingPlanMeter>,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct BillingAccount {
+    pub user_id: Uuid,
+    pub plan: String,
+    pub notes: Option<String>,
+    pub assigned_by: Option<Uuid>,
+    /// None while the account is on the default free plan
+    pub assigned_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct BillingStatement {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+    pub plan: String,
+    pub base_fee_usd: Decimal,
+    pub usage_fee_usd: Decimal,
+    pub total_usd: Decimal,
+    #[schema(value_type = Vec<MeterUsage>)]
+    pub line_items: sqlx::types::Json<Vec<MeterUsage>>,
+    pub generated_at: DateTime<Utc>,
+}
+
+// ============================================================================
+// Usage
+// ============================================================================
+
+/// Usage of one meter over a billing period and what it costs on the account's plan
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MeterUsage {
+    pub meter: BillingMeter,
+    /// Sum for counters, peak for gauges
+    pub quantity: i64,
+    pub included_quantity: i64,
+    pub billable_quantity: i64,
+    pub unit_price_usd: Option<Decimal>,
+    pub amount_usd: Decimal,
+}
+
+impl MeterUsage {
+    /// Price `quantity` against the plan's included quantity and overage price
+    pub fn price(meter: BillingMeter, quantity: i64, plan_meter: Option<&BillingPlanMeter>) -> Self {
+        let included_quantity = plan_meter.map(|m| m.included_quantity).unwrap_or(0);
+        let unit_price_usd = plan_meter.and_then(|m| m.overage_unit_price_usd);
+        let billable_quantity = (quantity - included_quantity).max(0);
+        let amount_usd = unit_price_usd
+            .map(|price| (price * Decimal::from(billable_quantity)).round_dp(2))
+            .unwrap_or_default();
+
+        Self { meter, quantity, included_quantity, billable_quantity, unit_price_usd, amount_usd }
+    }
+}
+
+/// Usage of an account over one billing period, priced on its current plan
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct UsageReport {
+    pub user_id: Uuid,
+    pub plan: String,
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+    pub meters: Vec<MeterUsage>,
+    pub base_fee_usd: Decimal,
+    pub usage_fee_usd: Decimal,
+    /// Final once the period has ended; the current period grows until then
+    pub total_usd: Decimal,
+}
+
+impl UsageReport {
+    pub fn new(
+        user_id: Uuid,
+        plan: &BillingPlanDetails,
+        (period_start, period_end): (NaiveDate, NaiveDate),
+        quantities: &[(BillingMeter, i64)],
+    ) -> Self {
+        let meters: Vec<MeterUsage> = BillingMeter::ALL
+            .into_iter()
+            .map(|meter| {
+                let quantity = quantities.iter().find(|(m, _)| *m == meter).map(|(_, q)| *q).unwrap_or(0);
+                let plan_meter = plan.meters.iter().find(|m| m.meter == meter.as_str());
+                MeterUsage::price(meter, quantity, plan_meter)
+            })
+            .collect();
+
+        let base_fee_usd = plan.plan.monthly_base_fee_usd;
+        let usage_fee_usd: Decimal = meters.iter().map(|m| m.amount_usd).sum();
+
+        Self {
+            user_id,
+            plan: plan.plan.plan.clone(),
+            period_start,
+            period_end,
+            meters,
+            base_fee_usd,
+            usage_fee_usd,
+            total_usd: base_fee_usd + usage_fee_usd,
+        }
+    }
+}
+
+// ============================================================================
+// Request Models
+// ============================================================================
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct BillingUsageQuery {
+    /// `YYYY-MM`; defaults to the current month
+    pub period: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AssignBillingPlanRequest {
+    pub plan: String,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct GenerateStatementsRequest {
+    /// `YYYY-MM` of a month that has ended
+    pub period: String,
+    /// Replace statements that were already generated for the period
+    #[serde(default)]
+    pub regenerate: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn plan_meter(meter: &str, included: i64, price: Option<&str>) -> BillingPlanMeter {
+        BillingPlanMeter {
+            plan: "pro".to_string(),
+            meter: meter.to_string(),
+            included_quantity: included,
+            overage_unit_price_usd: price.map(|p| Decimal::from_str(p).unwrap()),
+        }
+    }
+
+    #[test]
+    fn test_billing_period() {
+        let (start, end) = billing_period(NaiveDate::from_ymd_opt(2026, 12, 17).unwrap());
+        assert_eq!(start, NaiveDate::from_ymd_opt(2026, 12, 1).unwrap());
+        assert_eq!(end, NaiveDate::from_ymd_opt(2027, 1, 1).unwrap());
+
+        assert_eq!(parse_billing_month("2026-02"), NaiveDate::from_ymd_opt(2026, 2, 1));
+        assert_eq!(parse_billing_month("2026-13"), None);
+        assert_eq!(parse_billing_month("February"), None);
+    }
+
+    #[test]
+    fn test_meter_pricing() {
+        let listings = plan_meter("listings", 500, Some("0.10"));
+        let usage = MeterUsage::price(BillingMeter::Listings, 733, Some(&listings));
+        assert_eq!(usage.billable_quantity, 233);
+        assert_eq!(usage.amount_usd, Decimal::from_str("23.30").unwrap());
+
+        let under = MeterUsage::price(BillingMeter::Listings, 12, Some(&listings));
+        assert_eq!(under.billable_quantity, 0);
+        assert_eq!(under.amount_usd, Decimal::ZERO);
+
+        // No overage price: usage beyond the included quantity is not billed
+        let free = plan_meter("api_calls", 10_000, None);
+        let usage = MeterUsage::price(BillingMeter::ApiCalls, 25_000, Some(&free));
+        assert_eq!(usage.billable_quantity, 15_000);
+        assert_eq!(usage.amount_usd, Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_usage_report_totals() {
+        let plan = BillingPlanDetails {
+            plan: BillingPlan {
+                plan: "pro".to_string(),
+                display_name: "Pro".to_string(),
+                monthly_base_fee_usd: Decimal::from(199),
+                is_active: true,
+                updated_at: Utc::now(),
+            },
+            meters: vec![plan_meter("transactions", 200, Some("0.50")), plan_meter("erp_connections", 2, Some("25"))],
+        };
+        let period = billing_period(NaiveDate::from_ymd_opt(2026, 9, 1).unwrap());
+        let report = UsageReport::new(
+            Uuid::nil(),
+            &plan,
+            period,
+            &[(BillingMeter::Transactions, 210), (BillingMeter::ErpConnections, 3)],
+        );
+
+        assert_eq!(report.meters.len(), BillingMeter::ALL.len());
+        assert_eq!(report.usage_fee_usd, Decimal::from(30));
+        assert_eq!(report.total_usd, Decimal::from(229));
+    }
+}
@@ -0,0 +1,286 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use utoipa::IntoParams;
+use crate::middleware::error_handling::{AppError, Result};
+use crate::models::ema::EmaCatalogEntry;
+use crate::models::openfda::OpenFdaCatalogEntry;
+
+/// Rows fetched per database round trip while streaming an export
+pub const EXPORT_PAGE_SIZE: i64 = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Ndjson,
+}
+
+impl ExportFormat {
+    pub fn parse(format: Option<&str>) -> Result<Self> {
+        match format.map(|f| f.trim().to_lowercase()).as_deref() {
+            None | Some("") | Some("csv") => Ok(ExportFormat::Csv),
+            Some("ndjson") | Some("jsonl") => Ok(ExportFormat::Ndjson),
+            Some(other) => Err(AppError::BadRequest(format!(
+                "Unsupported export format '{}'. Use csv or ndjson",
+                other
+            ))),
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Ndjson => "ndjson",
+        }
+    }
+}
+
+/// A catalog row as exported. Rows are ordered by `export_key`, which is unique,
+/// so `after=<key>` and `Range: records=N-` resume an interrupted download.
+pub trait ExportRecord {
+    const COLUMNS: &'static [&'static str];
+
+    fn export_key(&self) -> &str;
+
+    /// Values in `COLUMNS` order
+    fn export_values(&self) -> Vec<Value>;
+}
+
+/// Encode rows in the export format. The CSV header is written only for the first
+/// chunk of a download that starts at the beginning of the catalog.
+pub fn encode_records<T: ExportRecord>(format: ExportFormat, records: &[T], with_header: bool) -> Result<Vec<u8>> {
+    match format {
+        ExportFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(Vec::new());
+            if with_header {
+                writer.write_record(T::COLUMNS).map_err(csv_error)?;
+            }
+            for record in records {
+                writer
+                    .write_record(record.export_values().iter().map(csv_field))
+                    .map_err(csv_error)?;
+            }
+            writer
+                .into_inner()
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to encode CSV: {}", e)))
+        }
+        ExportFormat::Ndjson => {
+            let mut buffer = Vec::new();
+            for record in records {
+                let object: serde_json::Map<String, Value> = T::COLUMNS
+                    .iter()
+                    .map(|c| c.to_string())
+                    .zip(record.export_values())
+                    .collect();
+                serde_json::to_writer(&mut buffer, &object)?;
+                buffer.push(b'\n');
+            }
+            Ok(buffer)
+        }
+    }
+}
+
+fn csv_error(e: csv::Error) -> AppError {
+    AppError::Internal(anyhow::anyhow!("Failed to encode CSV: {}", e))
+}
+
+/// Flatten a value into a CSV cell: lists of strings are joined with "; ", other
+/// structured values are embedded as JSON
+fn csv_field(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Array(items) if items.iter().all(Value::is_string) => items
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .join("; "),
+        other => other.to_string(),
+    }
+}
+
+/// Parse a `Range: records=N-` header into the number of records to skip.
+/// Returns `None` for other range units, which are ignored (a full export is sent).
+pub fn parse_records_range(header: &str) -> Option<Result<i64>> {
+    let spec = header.trim().strip_prefix("records=")?;
+    let start = spec.strip_suffix('-').unwrap_or(spec);
+
+    Some(
+        start
+            .trim()
+            .parse::<i64>()
+            .ok()
+            .filter(|n| *n >= 0)
+            .ok_or_else(|| AppError::BadRequest(format!(
+                "Invalid range '{}'. Only open-ended ranges of the form records=N- are supported",
+                header
+            ))),
+    )
+}
+
+/// Filters of `GET /api/openfda/export`
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct OpenFdaExportParams {
+    pub format: Option<String>,
+    pub manufacturer: Option<String>,
+    pub product_type: Option<String>,
+    pub marketing_category: Option<String>,
+    pub dosage_form: Option<String>,
+    pub dea_schedule: Option<String>,
+    pub updated_since: Option<DateTime<Utc>>,
+    /// Resume after this product NDC
+    pub after: Option<String>,
+}
+
+/// Filters of `GET /api/ema/export`
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EmaExportParams {
+    pub format: Option<String>,
+    pub language: Option<String>,
+    pub authorization_status: Option<String>,
+    pub therapeutic_area: Option<String>,
+    pub atc_code: Option<String>,
+    pub mah_name: Option<String>,
+    pub orphan_designation: Option<bool>,
+    pub updated_since: Option<DateTime<Utc>>,
+    /// Resume after this EU number
+    pub after: Option<String>,
+}
+
+impl ExportRecord for OpenFdaCatalogEntry {
+    const COLUMNS: &'static [&'static str] = &[
+        "product_ndc", "product_id", "brand_name", "generic_name", "labeler_name", "dosage_form",
+        "route", "strength", "active_ingredients", "product_type", "marketing_category",
+        "pharm_class", "dea_schedule", "packaging", "finished", "marketing_start_date",
+        "listing_expiration_date", "last_synced_at", "updated_at",
+    ];
+
+    fn export_key(&self) -> &str {
+        &self.product_ndc
+    }
+
+    fn export_values(&self) -> Vec<Value> {
+        vec![
+            json!(self.product_ndc),
+            json!(self.product_id),
+            json!(self.brand_name),
+            json!(self.generic_name),
+            json!(self.labeler_name),
+            json!(self.dosage_form),
+            json!(self.route),
+            json!(self.strength),
+            json!(self.active_ingredients),
+            json!(self.product_type),
+            json!(self.marketing_category),
+            json!(self.pharm_class),
+            json!(self.dea_schedule),
+            json!(self.packaging),
+            json!(self.finished),
+            json!(self.marketing_start_date),
+            json!(self.listing_expiration_date),
+            json!(self.last_synced_at),
+            json!(self.updated_at),
+        ]
+    }
+}
+
+impl ExportRecord for EmaCatalogEntry {
+    const COLUMNS: &'static [&'static str] = &[
+        "eu_number", "product_name", "inn_name", "therapeutic_indication", "mah_name", "mah_country",
+        "authorization_status", "authorization_date", "procedure_type", "pharmaceutical_form",
+        "route_of_administration", "strength", "active_substances", "atc_code", "therapeutic_area",
+        "orphan_designation", "additional_monitoring", "language_code", "epi_url", "smpc_url",
+        "pil_url", "last_synced_at", "updated_at",
+    ];
+
+    fn export_key(&self) -> &str {
+        &self.eu_number
+    }
+
+    fn export_values(&self) -> Vec<Value> {
+        vec![
+            json!(self.eu_number),
+            json!(self.product_name),
+            json!(self.inn_name),
+            json!(self.therapeutic_indication),
+            json!(self.mah_name),
+            json!(self.mah_country),
+            json!(self.authorization_status),
+            json!(self.authorization_date),
+            json!(self.procedure_type),
+            json!(self.pharmaceutical_form),
+            json!(self.route_of_administration),
+            json!(self.strength),
+            json!(self.active_substances),
+            json!(self.atc_code),
+            json!(self.therapeutic_area),
+            json!(self.orphan_designation),
+            json!(self.additional_monitoring),
+            json!(self.language_code),
+            json!(self.epi_url),
+            json!(self.smpc_url),
+            json!(self.pil_url),
+            json!(self.last_synced_at),
+            json!(self.updated_at),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Row(&'static str, Value);
+
+    impl ExportRecord for Row {
+        const COLUMNS: &'static [&'static str] = &["key", "value"];
+
+        fn export_key(&self) -> &str {
+            self.0
+        }
+
+        fn export_values(&self) -> Vec<Value> {
+            vec![json!(self.0), self.1.clone()]
+        }
+    }
+
+    #[test]
+    fn test_encode_csv_flattens_values() {
+        let rows = [
+            Row("a", json!(["ORAL", "TOPICAL"])),
+            Row("b", json!({"name": "x, y"})),
+            Row("c", Value::Null),
+        ];
+
+        let csv = String::from_utf8(encode_records(ExportFormat::Csv, &rows, true).unwrap()).unwrap();
+        assert_eq!(csv, "key,value\na,ORAL; TOPICAL\nb,\"{\"\"name\"\":\"\"x, y\"\"}\"\nc,\n");
+
+        let resumed = String::from_utf8(encode_records(ExportFormat::Csv, &rows[2..], false).unwrap()).unwrap();
+        assert_eq!(resumed, "c,\n");
+    }
+
+    #[test]
+    fn test_encode_ndjson() {
+        let rows = [Row("a", json!(1)), Row("b", Value::Null)];
+        let ndjson = String::from_utf8(encode_records(ExportFormat::Ndjson, &rows, true).unwrap()).unwrap();
+        assert_eq!(ndjson, "{\"key\":\"a\",\"value\":1}\n{\"key\":\"b\",\"value\":null}\n");
+    }
+
+    #[test]
+    fn test_parse_records_range() {
+        assert_eq!(parse_records_range("records=250-").unwrap().unwrap(), 250);
+        assert_eq!(parse_records_range("records=0-").unwrap().unwrap(), 0);
+        assert!(parse_records_range("records=-5").unwrap().is_err());
+        assert!(parse_records_range("records=1-2").unwrap().is_err());
+        assert!(parse_records_range("bytes=0-100").is_none());
+    }
+}
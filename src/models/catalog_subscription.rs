@@ -0,0 +1,181 @@
+/// Catalog change-subscription models
+///
+/// Users subscribe to individual OpenFDA (NDC) or EMA (EU number) records and are
+/// notified when a sync run changes their authorization status, discontinues them,
+/// or flags a recall.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+// ============================================================================
+// ENUMS
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CatalogSource {
+    Openfda,
+    Ema,
+}
+
+impl CatalogSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CatalogSource::Openfda => "openfda",
+            CatalogSource::Ema => "ema",
+        }
+    }
+}
+
+impl std::fmt::Display for CatalogSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CatalogChangeType {
+    StatusChange,
+    Discontinuation,
+    Recall,
+}
+
+impl CatalogChangeType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CatalogChangeType::StatusChange => "status_change",
+            CatalogChangeType::Discontinuation => "discontinuation",
+            CatalogChangeType::Recall => "recall",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            CatalogChangeType::StatusChange => "Authorization status changed",
+            CatalogChangeType::Discontinuation => "Product discontinued",
+            CatalogChangeType::Recall => "Recall or suspension",
+        }
+    }
+}
+
+impl std::fmt::Display for CatalogChangeType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+// ============================================================================
+// DATABASE MODELS
+// ============================================================================
+
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct CatalogSubscription {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub source: String,
+    pub identifier: String,
+    pub change_types: Vec<String>,
+    pub notify_in_app: bool,
+    pub webhook_url: Option<String>,
+    #[serde(skip_serializing)]
+    pub webhook_secret_encrypted: Option<String>,
+    pub is_active: bool,
+    pub last_notified_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct CatalogChangeEvent {
+    pub id: Uuid,
+    pub source: String,
+    pub identifier: String,
+    pub product_name: Option<String>,
+    pub change_type: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub details: serde_json::Value,
+    pub sync_log_id: Option<Uuid>,
+    pub detected_at: DateTime<Utc>,
+    pub dispatched_at: Option<DateTime<Utc>>,
+}
+
+/// The fields of a catalog record that change detection looks at
+#[derive(Debug, Clone, FromRow)]
+pub struct CatalogSnapshot {
+    pub identifier: String,
+    pub product_name: String,
+    /// OpenFDA marketing_category / EMA authorization_status
+    pub status: Option<String>,
+    /// OpenFDA listing_expiration_date (EMA has no equivalent)
+    pub expiration_date: Option<NaiveDate>,
+}
+
+/// A change found by diffing a catalog record before and after upsert
+#[derive(Debug, Clone)]
+pub struct DetectedCatalogChange {
+    pub source: CatalogSource,
+    pub identifier: String,
+    pub product_name: String,
+    pub change_type: CatalogChangeType,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+}
+
+// ============================================================================
+// API REQUEST MODELS
+// ============================================================================
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateCatalogSubscriptionRequest {
+    pub source: CatalogSource,
+    pub identifier: String,
+    pub change_types: Option<Vec<CatalogChangeType>>,
+    pub notify_in_app: Option<bool>,
+    pub webhook_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateCatalogSubscriptionRequest {
+    pub change_types: Option<Vec<CatalogChangeType>>,
+    pub notify_in_app: Option<bool>,
+    pub webhook_url: Option<String>,
+    pub is_active: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CatalogChangeEventsQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+// ============================================================================
+// API RESPONSE MODELS
+// ============================================================================
+
+/// Returned on creation only - the webhook secret is never shown again
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CreatedCatalogSubscriptionResponse {
+    #[serde(flatten)]
+    pub subscription: CatalogSubscription,
+    pub webhook_secret: Option<String>,
+}
+
+/// Body POSTed to subscriber webhooks
+#[derive(Debug, Serialize)]
+pub struct CatalogChangeWebhookPayload {
+    pub event_id: Uuid,
+    pub subscription_id: Uuid,
+    pub source: String,
+    pub identifier: String,
+    pub product_name: Option<String>,
+    pub change_type: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub detected_at: DateTime<Utc>,
+}
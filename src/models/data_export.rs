@@ -0,0 +1,381 @@
+/// Data export models: asynchronous exports written to file storage by a background job,
+/// their filters and the rows of each export type
+
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use utoipa::{IntoParams, ToSchema};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::middleware::error_handling::{AppError, Result};
+use crate::models::catalog_export::{EmaExportParams, ExportFormat, ExportRecord, OpenFdaExportParams};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DataExportType {
+    /// The caller's inventory
+    Inventory,
+    /// The caller's sales and purchases
+    Transactions,
+    /// Audit logs (admins only)
+    AuditLogs,
+    OpenFdaCatalog,
+    EmaCatalog,
+}
+
+impl DataExportType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DataExportType::Inventory => "inventory",
+            DataExportType::Transactions => "transactions",
+            DataExportType::AuditLogs => "audit_logs",
+            DataExportType::OpenFdaCatalog => "openfda_catalog",
+            DataExportType::EmaCatalog => "ema_catalog",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "inventory" => Some(DataExportType::Inventory),
+            "transactions" => Some(DataExportType::Transactions),
+            "audit_logs" => Some(DataExportType::AuditLogs),
+            "openfda_catalog" => Some(DataExportType::OpenFdaCatalog),
+            "ema_catalog" => Some(DataExportType::EmaCatalog),
+            _ => None,
+        }
+    }
+
+    pub fn requires_admin(&self) -> bool {
+        matches!(self, DataExportType::AuditLogs)
+    }
+
+    /// Check the filters of a new export parse for this type
+    pub fn check_filters(&self, filters: &Value) -> Result<()> {
+        let parsed = match self {
+            DataExportType::Inventory => serde_json::from_value::<InventoryExportFilters>(filters.clone()).map(drop),
+            DataExportType::Transactions => serde_json::from_value::<TransactionExportFilters>(filters.clone()).map(drop),
+            DataExportType::AuditLogs => serde_json::from_value::<AuditLogExportFilters>(filters.clone()).map(drop),
+            DataExportType::OpenFdaCatalog => serde_json::from_value::<OpenFdaExportParams>(filters.clone()).map(drop),
+            DataExportType::EmaCatalog => serde_json::from_value::<EmaExportParams>(filters.clone()).map(drop),
+        };
+        parsed.map_err(|e| AppError::BadRequest(format!("Invalid {} export filters: {}", self.as_str(), e)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DataExportStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+    /// The file was deleted after `expires_at`
+    Expired,
+}
+
+impl DataExportStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DataExportStatus::Queued => "queued",
+            DataExportStatus::Running => "running",
+            DataExportStatus::Completed => "completed",
+            DataExportStatus::Failed => "failed",
+            DataExportStatus::Expired => "expired",
+        }
+    }
+}
+
+// ============================================================================
+// DATABASE MODELS
+// ============================================================================
+
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct DataExport {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub export_type: String,
+    pub format: String,
+    pub filters: Value,
+    pub status: String,
+    pub total_rows: Option<i64>,
+    pub rows_written: i64,
+    #[serde(skip_serializing)]
+    pub file_path: Option<String>,
+    pub file_size_bytes: Option<i64>,
+    pub content_hash: Option<String>,
+    pub error: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl DataExport {
+    pub fn export_format(&self) -> ExportFormat {
+        ExportFormat::parse(Some(&self.format)).unwrap_or(ExportFormat::Csv)
+    }
+
+    /// Download name, e.g. `inventory-20250101-120000.csv`
+    pub fn filename(&self) -> String {
+        format!(
+            "{}-{}.{}",
+            self.export_type.replace('_', "-"),
+            self.created_at.format("%Y%m%d-%H%M%S"),
+            self.export_format().extension()
+        )
+    }
+}
+
+// ============================================================================
+// FILTERS
+// ============================================================================
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InventoryExportFilters {
+    pub status: Option<String>,
+    /// Lots expiring on or before this date
+    pub expiring_before: Option<NaiveDate>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TransactionExportFilters {
+    pub status: Option<String>,
+    /// "seller" or "buyer"; both when unset
+    pub role: Option<String>,
+    pub from_date: Option<NaiveDate>,
+    pub to_date: Option<NaiveDate>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AuditLogExportFilters {
+    pub event_category: Option<String>,
+    pub severity: Option<String>,
+    pub actor_user_id: Option<Uuid>,
+    pub from_date: Option<NaiveDate>,
+    pub to_date: Option<NaiveDate>,
+}
+
+// ============================================================================
+// EXPORTED ROWS
+// ============================================================================
+
+/// Rows are ordered by their id; `export_key` is the id as text
+#[derive(Debug, Clone, FromRow)]
+pub struct InventoryExportRow {
+    pub inventory_id: String,
+    pub brand_name: String,
+    pub generic_name: String,
+    pub ndc_code: Option<String>,
+    pub manufacturer: String,
+    pub batch_number: String,
+    pub quantity: i32,
+    pub expiry_date: NaiveDate,
+    pub unit_price: Option<Decimal>,
+    pub storage_location: Option<String>,
+    pub status: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl ExportRecord for InventoryExportRow {
+    const COLUMNS: &'static [&'static str] = &[
+        "inventory_id", "brand_name", "generic_name", "ndc_code", "manufacturer", "batch_number",
+        "quantity", "expiry_date", "unit_price", "storage_location", "status", "created_at", "updated_at",
+    ];
+
+    fn export_key(&self) -> &str {
+        &self.inventory_id
+    }
+
+    fn export_values(&self) -> Vec<Value> {
+        vec![
+            json!(self.inventory_id),
+            json!(self.brand_name),
+            json!(self.generic_name),
+            json!(self.ndc_code),
+            json!(self.manufacturer),
+            json!(self.batch_number),
+            json!(self.quantity),
+            json!(self.expiry_date),
+            json!(self.unit_price),
+            json!(self.storage_location),
+            json!(self.status),
+            json!(self.created_at),
+            json!(self.updated_at),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct TransactionExportRow {
+    pub transaction_id: String,
+    /// The exporting user's side of the trade: "seller" or "buyer"
+    pub role: String,
+    pub inquiry_id: Uuid,
+    pub seller_id: Uuid,
+    pub buyer_id: Uuid,
+    pub quantity: i32,
+    pub unit_price: Decimal,
+    pub total_price: Decimal,
+    pub status: Option<String>,
+    pub transaction_date: Option<DateTime<Utc>>,
+    pub erp_sales_order_number: Option<String>,
+    pub erp_purchase_order_number: Option<String>,
+    pub sanctions_status: Option<String>,
+}
+
+impl ExportRecord for TransactionExportRow {
+    const COLUMNS: &'static [&'static str] = &[
+        "transaction_id", "role", "inquiry_id", "seller_id", "buyer_id", "quantity", "unit_price",
+        "total_price", "status", "transaction_date", "erp_sales_order_number",
+        "erp_purchase_order_number", "sanctions_status",
+    ];
+
+    fn export_key(&self) -> &str {
+        &self.transaction_id
+    }
+
+    fn export_values(&self) -> Vec<Value> {
+        vec![
+            json!(self.transaction_id),
+            json!(self.role),
+            json!(self.inquiry_id),
+            json!(self.seller_id),
+            json!(self.buyer_id),
+            json!(self.quantity),
+            json!(self.unit_price),
+            json!(self.total_price),
+            json!(self.status),
+            json!(self.transaction_date),
+            json!(self.erp_sales_order_number),
+            json!(self.erp_purchase_order_number),
+            json!(self.sanctions_status),
+        ]
+    }
+}
+
+/// `export_key` is the numeric id as text; pages continue after it numerically
+#[derive(Debug, Clone, FromRow)]
+pub struct AuditLogExportRow {
+    pub export_key: String,
+    pub event_id: Uuid,
+    pub event_type: String,
+    pub event_category: String,
+    pub severity: String,
+    pub actor_user_id: Option<Uuid>,
+    pub actor_type: String,
+    pub resource_type: Option<String>,
+    pub resource_id: Option<String>,
+    pub action: String,
+    pub action_result: String,
+    pub event_data: Value,
+    pub ip_address: Option<String>,
+    pub request_id: Option<String>,
+    pub entry_hash: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ExportRecord for AuditLogExportRow {
+    const COLUMNS: &'static [&'static str] = &[
+        "id", "event_id", "event_type", "event_category", "severity", "actor_user_id", "actor_type",
+        "resource_type", "resource_id", "action", "action_result", "event_data", "ip_address",
+        "request_id", "entry_hash", "created_at",
+    ];
+
+    fn export_key(&self) -> &str {
+        &self.export_key
+    }
+
+    fn export_values(&self) -> Vec<Value> {
+        vec![
+            json!(self.export_key),
+            json!(self.event_id),
+            json!(self.event_type),
+            json!(self.event_category),
+            json!(self.severity),
+            json!(self.actor_user_id),
+            json!(self.actor_type),
+            json!(self.resource_type),
+            json!(self.resource_id),
+            json!(self.action),
+            json!(self.action_result),
+            self.event_data.clone(),
+            json!(self.ip_address),
+            json!(self.request_id),
+            json!(self.entry_hash),
+            json!(self.created_at),
+        ]
+    }
+}
+
+// ============================================================================
+// REQUEST/RESPONSE MODELS
+// ============================================================================
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateDataExportRequest {
+    pub export_type: DataExportType,
+    /// csv (default) or ndjson
+    pub format: Option<String>,
+    /// Filters of the export type; catalog exports take the filters of their streaming
+    /// export endpoints
+    #[serde(default)]
+    pub filters: Option<Value>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DataExportQuery {
+    pub limit: Option<i64>,
+}
+
+/// Query of a signed download link
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DataExportDownloadQuery {
+    /// Unix time the link expires
+    pub expires: i64,
+    pub signature: String,
+}
+
+/// An export with its progress and, once completed, a download link
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct DataExportResponse {
+    #[serde(flatten)]
+    pub export: DataExport,
+    /// Share of the rows written, in percent (None before the rows are counted)
+    pub progress_percent: Option<f64>,
+    pub download_url: Option<String>,
+    pub download_url_expires_at: Option<DateTime<Utc>>,
+}
+
+impl DataExportResponse {
+    pub fn progress_percent(export: &DataExport) -> Option<f64> {
+        match (export.status.as_str(), export.total_rows) {
+            ("completed", _) => Some(100.0),
+            (_, Some(0)) => Some(0.0),
+            (_, Some(total)) => Some(((export.rows_written as f64 / total as f64) * 1000.0).round() / 10.0),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_filters_rejects_unknown_fields() {
+        assert!(DataExportType::Inventory.check_filters(&json!({ "status": "available" })).is_ok());
+        assert!(DataExportType::Inventory.check_filters(&json!({ "buyer_id": "x" })).is_err());
+        assert!(DataExportType::AuditLogs.check_filters(&json!({ "from_date": "2025-01-01" })).is_ok());
+        assert!(DataExportType::Transactions.check_filters(&json!({ "from_date": "yesterday" })).is_err());
+        assert!(DataExportType::OpenFdaCatalog.check_filters(&json!({ "manufacturer": "Pfizer" })).is_ok());
+    }
+}
@@ -0,0 +1,140 @@
+/// Data retention models: retention periods per data category and purge run reports
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Rows deleted per statement, so a purge never holds long locks
+pub const PURGE_BATCH_SIZE: i64 = 5000;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionCategory {
+    AuditLogs,
+    Notifications,
+    SyncLogs,
+    Messages,
+    ArchivedInventory,
+}
+
+impl RetentionCategory {
+    pub const ALL: [RetentionCategory; 5] = [
+        RetentionCategory::AuditLogs,
+        RetentionCategory::Notifications,
+        RetentionCategory::SyncLogs,
+        RetentionCategory::Messages,
+        RetentionCategory::ArchivedInventory,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RetentionCategory::AuditLogs => "audit_logs",
+            RetentionCategory::Notifications => "notifications",
+            RetentionCategory::SyncLogs => "sync_logs",
+            RetentionCategory::Messages => "messages",
+            RetentionCategory::ArchivedInventory => "archived_inventory",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.as_str() == value)
+    }
+
+    /// Shortest period a superadmin may set. Audit logs are kept at least six years
+    /// (HIPAA, DSCSA); lots and messages back transaction records.
+    pub fn minimum_days(&self) -> i32 {
+        match self {
+            RetentionCategory::AuditLogs => 2190,
+            RetentionCategory::Notifications => 30,
+            RetentionCategory::SyncLogs => 30,
+            RetentionCategory::Messages => 365,
+            RetentionCategory::ArchivedInventory => 365,
+        }
+    }
+}
+
+// ============================================================================
+// DATABASE MODELS
+// ============================================================================
+
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct DataRetentionPolicy {
+    pub category: String,
+    pub retention_days: i32,
+    pub is_enabled: bool,
+    pub updated_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl DataRetentionPolicy {
+    /// Rows created before this are due for purging
+    pub fn cutoff(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        now - Duration::days(self.retention_days as i64)
+    }
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct DataPurgeRun {
+    pub id: Uuid,
+    pub status: String,
+    pub triggered_by: Option<Uuid>,
+    pub started_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub total_purged: i64,
+    pub report: serde_json::Value,
+    pub error: Option<String>,
+}
+
+// ============================================================================
+// REQUEST / RESPONSE MODELS
+// ============================================================================
+
+/// PUT body: omitted fields are left unchanged
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateRetentionPolicyRequest {
+    pub retention_days: Option<i32>,
+    pub is_enabled: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PurgeRunQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+// ============================================================================
+// PURGE REPORT
+// ============================================================================
+
+/// What a run did with one category
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CategoryPurgeReport {
+    pub category: RetentionCategory,
+    pub retention_days: i32,
+    pub cutoff: DateTime<Utc>,
+    pub purged: i64,
+    /// Rows past the cutoff that were kept (legal hold, or lots still referenced)
+    pub retained: i64,
+    /// Users and transactions whose data was exempt through a legal hold
+    pub held_user_ids: Vec<Uuid>,
+    pub held_transaction_ids: Vec<Uuid>,
+    /// Set when the category was not purged at all
+    pub skipped_reason: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_category_round_trip() {
+        for category in RetentionCategory::ALL {
+            assert_eq!(RetentionCategory::parse(category.as_str()), Some(category));
+        }
+        assert_eq!(RetentionCategory::parse("inventory"), None);
+    }
+}
@@ -0,0 +1,239 @@
+/// Regulatory document batch models: bulk generation of one document type for a
+/// filtered set of the seller's transactions, with per-transaction results
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Transactions a single batch may cover
+pub const MAX_BATCH_ITEMS: i64 = 200;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum BatchDocumentType {
+    /// DSCSA Transaction Information, History and Statement
+    Dscsa,
+    Coa,
+    Gdp,
+    Gmp,
+}
+
+impl BatchDocumentType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BatchDocumentType::Dscsa => "DSCSA",
+            BatchDocumentType::Coa => "COA",
+            BatchDocumentType::Gdp => "GDP",
+            BatchDocumentType::Gmp => "GMP",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "DSCSA" => Some(BatchDocumentType::Dscsa),
+            "COA" => Some(BatchDocumentType::Coa),
+            "GDP" => Some(BatchDocumentType::Gdp),
+            "GMP" => Some(BatchDocumentType::Gmp),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DocumentBatchStatus {
+    Queued,
+    Running,
+    Completed,
+    CompletedWithErrors,
+    Failed,
+}
+
+impl DocumentBatchStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DocumentBatchStatus::Queued => "queued",
+            DocumentBatchStatus::Running => "running",
+            DocumentBatchStatus::Completed => "completed",
+            DocumentBatchStatus::CompletedWithErrors => "completed_with_errors",
+            DocumentBatchStatus::Failed => "failed",
+        }
+    }
+
+    /// Final status of a batch whose items have all been processed
+    pub fn finished(succeeded: i32, failed: i32) -> Self {
+        match (succeeded, failed) {
+            (_, 0) => DocumentBatchStatus::Completed,
+            (0, _) => DocumentBatchStatus::Failed,
+            _ => DocumentBatchStatus::CompletedWithErrors,
+        }
+    }
+}
+
+// ============================================================================
+// DATABASE MODELS
+// ============================================================================
+
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct DocumentBatch {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub document_type: String,
+    pub filters: serde_json::Value,
+    pub status: String,
+    pub total_items: i32,
+    pub processed_items: i32,
+    pub succeeded_items: i32,
+    pub failed_items: i32,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl DocumentBatch {
+    pub fn is_finished(&self) -> bool {
+        !matches!(self.status.as_str(), "queued" | "running")
+    }
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct DocumentBatchItem {
+    pub id: Uuid,
+    pub batch_id: Uuid,
+    pub transaction_id: Uuid,
+    pub position: i32,
+    pub status: String,
+    pub document_ids: Vec<Uuid>,
+    pub error: Option<String>,
+    pub processed_at: Option<DateTime<Utc>>,
+}
+
+/// Transaction data an AI-generated document of a batch is built from
+#[derive(Debug, Clone, FromRow)]
+pub struct BatchTransactionLot {
+    pub transaction_id: Uuid,
+    pub transaction_date: DateTime<Utc>,
+    pub quantity: i32,
+    pub buyer_company: String,
+    pub batch_number: String,
+    pub expiry_date: NaiveDate,
+    pub brand_name: String,
+    pub manufacturer: String,
+    pub ndc_code: Option<String>,
+}
+
+/// A generated document as written to the batch archive
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct BatchArchiveDocument {
+    pub id: Uuid,
+    pub document_type: String,
+    pub document_number: String,
+    pub title: String,
+    pub status: String,
+    pub content: serde_json::Value,
+    pub content_hash: String,
+    pub generated_signature: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+// ============================================================================
+// REQUEST/RESPONSE MODELS
+// ============================================================================
+
+/// Transactions the batch covers: the given ids, or the seller's transactions matching
+/// the filters
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, ToSchema)]
+pub struct CreateDocumentBatchRequest {
+    pub document_type: BatchDocumentType,
+    #[validate(length(min = 1, max = 200, message = "Between 1 and 200 transaction ids"))]
+    pub transaction_ids: Option<Vec<Uuid>>,
+    /// Transaction status ('pending', 'completed', 'cancelled')
+    pub status: Option<String>,
+    pub buyer_id: Option<Uuid>,
+    pub from_date: Option<NaiveDate>,
+    pub to_date: Option<NaiveDate>,
+}
+
+impl CreateDocumentBatchRequest {
+    pub fn check_filters(&self) -> Result<(), String> {
+        if let Some(status) = self.status.as_deref() {
+            if !matches!(status, "pending" | "completed" | "cancelled") {
+                return Err(format!("Unknown transaction status '{}'", status));
+            }
+        }
+        if let (Some(from), Some(to)) = (self.from_date, self.to_date) {
+            if from > to {
+                return Err("from_date must not be after to_date".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DocumentBatchQuery {
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DocumentBatchDetail {
+    #[serde(flatten)]
+    pub batch: DocumentBatch,
+    pub items: Vec<DocumentBatchItem>,
+}
+
+/// File name of a document inside the batch archive
+pub fn archive_entry_name(transaction_id: Uuid, document_number: &str) -> String {
+    let number: String = document_number
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    format!("{}/{}.json", transaction_id, number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_finished_status() {
+        assert_eq!(DocumentBatchStatus::finished(5, 0), DocumentBatchStatus::Completed);
+        assert_eq!(DocumentBatchStatus::finished(3, 2), DocumentBatchStatus::CompletedWithErrors);
+        assert_eq!(DocumentBatchStatus::finished(0, 4), DocumentBatchStatus::Failed);
+    }
+
+    #[test]
+    fn test_check_filters() {
+        let mut request = CreateDocumentBatchRequest {
+            document_type: BatchDocumentType::Dscsa,
+            transaction_ids: None,
+            status: Some("completed".to_string()),
+            buyer_id: None,
+            from_date: NaiveDate::from_ymd_opt(2026, 1, 1),
+            to_date: NaiveDate::from_ymd_opt(2026, 3, 31),
+        };
+        assert!(request.check_filters().is_ok());
+
+        request.status = Some("shipped".to_string());
+        assert!(request.check_filters().is_err());
+
+        request.status = None;
+        request.from_date = NaiveDate::from_ymd_opt(2026, 4, 1);
+        assert!(request.check_filters().is_err());
+    }
+
+    #[test]
+    fn test_archive_entry_name() {
+        let id = Uuid::nil();
+        assert_eq!(
+            archive_entry_name(id, "DSCSA-TS/2026 001"),
+            "00000000-0000-0000-0000-000000000000/DSCSA-TS_2026_001.json"
+        );
+    }
+}
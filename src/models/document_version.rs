@@ -0,0 +1,224 @@
+/// Regulatory document versioning models: revisions under change control, version
+/// lineage and content diffs between versions
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Statuses from which a document can be revised. Drafts are not revised: they are
+/// still being worked on, and superseded, obsolete or voided documents are out of use.
+pub const REVISABLE_STATUSES: [&str; 2] = ["approved", "rejected"];
+
+/// Statuses of a version still open for signing; a lineage has at most one
+pub const OPEN_STATUSES: [&str; 2] = ["draft", "pending_approval"];
+
+/// Changes listed in a diff before it is truncated
+pub const MAX_DIFF_CHANGES: usize = 500;
+
+// ============================================================================
+// DATABASE MODELS
+// ============================================================================
+
+/// One version in a document's lineage
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct DocumentVersion {
+    pub id: Uuid,
+    pub root_document_id: Uuid,
+    pub version_number: i32,
+    pub previous_version_id: Option<Uuid>,
+    pub document_number: String,
+    pub document_type: String,
+    pub title: String,
+    pub status: String,
+    pub content_hash: String,
+    pub change_reason: Option<String>,
+    pub generated_by: Uuid,
+    pub approved_by: Option<Uuid>,
+    pub approved_at: Option<DateTime<Utc>>,
+    pub superseded_by: Option<Uuid>,
+    pub superseded_at: Option<DateTime<Utc>>,
+    pub obsoleted_by: Option<Uuid>,
+    pub obsolete_reason: Option<String>,
+    pub obsoleted_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+// ============================================================================
+// REQUEST / RESPONSE MODELS
+// ============================================================================
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct ReviseDocumentRequest {
+    /// Change control: why the document is revised
+    #[validate(length(min = 1, max = 2000, message = "Change reason must be 1-2000 characters"))]
+    pub change_reason: String,
+    /// Content of the new version (a JSON object)
+    pub content: serde_json::Value,
+    #[validate(length(min = 1, max = 500, message = "Title must be 1-500 characters"))]
+    pub title: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct ObsoleteDocumentRequest {
+    #[validate(length(min = 1, max = 2000, message = "Reason must be 1-2000 characters"))]
+    pub reason: String,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DocumentDiffQuery {
+    /// Version to compare with; defaults to the previous version
+    pub against: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DocumentVersionHistory {
+    pub root_document_id: Uuid,
+    /// Latest version in force (approved), if any
+    pub current_version_id: Option<Uuid>,
+    pub versions: Vec<DocumentVersion>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+/// A changed value, addressed by a JSON pointer into the content
+#[derive(Debug, Clone, Serialize, PartialEq, ToSchema)]
+pub struct ContentChange {
+    pub path: String,
+    pub change: ChangeKind,
+    pub old_value: Option<serde_json::Value>,
+    pub new_value: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DocumentDiff {
+    pub from_document_id: Uuid,
+    pub from_version: i32,
+    pub to_document_id: Uuid,
+    pub to_version: i32,
+    pub title_changed: bool,
+    pub changes: Vec<ContentChange>,
+    /// More than MAX_DIFF_CHANGES changes; the rest are omitted
+    pub truncated: bool,
+}
+
+// ============================================================================
+// HELPERS
+// ============================================================================
+
+/// Document number of a version: the base number, suffixed from version 2 on
+pub fn version_document_number(base_document_number: &str, version_number: i32) -> String {
+    if version_number <= 1 {
+        base_document_number.to_string()
+    } else {
+        format!("{}-V{}", base_document_number, version_number)
+    }
+}
+
+/// Structural diff of two content documents. Objects are compared key by key and
+/// arrays element by element; any other difference is a modification of the value.
+pub fn diff_content(old: &serde_json::Value, new: &serde_json::Value) -> Vec<ContentChange> {
+    let mut changes = Vec::new();
+    diff_value("", old, new, &mut changes);
+    changes
+}
+
+fn diff_value(path: &str, old: &serde_json::Value, new: &serde_json::Value, changes: &mut Vec<ContentChange>) {
+    use serde_json::Value;
+
+    match (old, new) {
+        (Value::Object(old_map), Value::Object(new_map)) => {
+            for (key, old_value) in old_map {
+                let child = format!("{}/{}", path, escape_pointer(key));
+                match new_map.get(key) {
+                    Some(new_value) => diff_value(&child, old_value, new_value, changes),
+                    None => changes.push(removed(child, old_value)),
+                }
+            }
+            for (key, new_value) in new_map.iter().filter(|(k, _)| !old_map.contains_key(*k)) {
+                changes.push(added(format!("{}/{}", path, escape_pointer(key)), new_value));
+            }
+        }
+        (Value::Array(old_items), Value::Array(new_items)) => {
+            for i in 0..old_items.len().max(new_items.len()) {
+                let child = format!("{}/{}", path, i);
+                match (old_items.get(i), new_items.get(i)) {
+                    (Some(o), Some(n)) => diff_value(&child, o, n, changes),
+                    (Some(o), None) => changes.push(removed(child, o)),
+                    (None, Some(n)) => changes.push(added(child, n)),
+                    (None, None) => {}
+                }
+            }
+        }
+        _ if old != new => changes.push(ContentChange {
+            path: path.to_string(),
+            change: ChangeKind::Modified,
+            old_value: Some(old.clone()),
+            new_value: Some(new.clone()),
+        }),
+        _ => {}
+    }
+}
+
+fn added(path: String, value: &serde_json::Value) -> ContentChange {
+    ContentChange { path, change: ChangeKind::Added, old_value: None, new_value: Some(value.clone()) }
+}
+
+fn removed(path: String, value: &serde_json::Value) -> ContentChange {
+    ContentChange { path, change: ChangeKind::Removed, old_value: Some(value.clone()), new_value: None }
+}
+
+/// RFC 6901 escaping of a key in a JSON pointer
+fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_version_document_number() {
+        assert_eq!(version_document_number("CoA-2026-000042", 1), "CoA-2026-000042");
+        assert_eq!(version_document_number("CoA-2026-000042", 3), "CoA-2026-000042-V3");
+    }
+
+    #[test]
+    fn test_diff_content() {
+        let old = json!({
+            "header": {"batch": "LOT-1", "site": "Basel"},
+            "tests": [{"name": "Assay", "result": "99.1%"}, {"name": "pH", "result": "6.8"}],
+            "notes": "initial"
+        });
+        let new = json!({
+            "header": {"batch": "LOT-1", "site": "Basel", "a/b": 1},
+            "tests": [{"name": "Assay", "result": "99.4%"}],
+        });
+
+        let changes = diff_content(&old, &new);
+        let summary: Vec<(&str, ChangeKind)> = changes.iter().map(|c| (c.path.as_str(), c.change)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("/header/a~1b", ChangeKind::Added),
+                ("/notes", ChangeKind::Removed),
+                ("/tests/0/result", ChangeKind::Modified),
+                ("/tests/1", ChangeKind::Removed),
+            ]
+        );
+        assert_eq!(changes[2].old_value, Some(json!("99.1%")));
+        assert_eq!(changes[2].new_value, Some(json!("99.4%")));
+
+        assert!(diff_content(&old, &old).is_empty());
+    }
+}
@@ -0,0 +1,360 @@
+/// DSCSA transaction data models: Transaction Information (TI), Transaction History (TH)
+/// and Transaction Statement (TS) for sales between US trading partners
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+use serde_json::json;
+use utoipa::ToSchema;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Statute the documents are issued under
+pub const DSCSA_STANDARD: &str = "FD&C Act §582 (Drug Supply Chain Security Act)";
+
+/// Country both trading partners have to be in
+pub const DSCSA_COUNTRY: &str = "US";
+
+/// Attestations of the transferring trading partner, FD&C Act §581(27)
+pub const TRANSACTION_STATEMENT: [&str; 7] = [
+    "is authorized as required under the Drug Supply Chain Security Act",
+    "received the product from a person that is authorized as required under the Drug Supply Chain Security Act",
+    "received transaction information and a transaction statement from the prior owner of the product, as required under section 582",
+    "did not knowingly ship a suspect or illegitimate product",
+    "had systems and processes in place to comply with verification requirements under section 582",
+    "did not knowingly provide false transaction information",
+    "did not knowingly alter the transaction history",
+];
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DscsaDocumentKind {
+    TransactionInformation,
+    TransactionHistory,
+    TransactionStatement,
+}
+
+impl DscsaDocumentKind {
+    pub const ALL: [DscsaDocumentKind; 3] = [
+        DscsaDocumentKind::TransactionInformation,
+        DscsaDocumentKind::TransactionHistory,
+        DscsaDocumentKind::TransactionStatement,
+    ];
+
+    /// regulatory_documents.document_type
+    pub fn document_type(&self) -> &'static str {
+        match self {
+            DscsaDocumentKind::TransactionInformation => "DSCSA_TI",
+            DscsaDocumentKind::TransactionHistory => "DSCSA_TH",
+            DscsaDocumentKind::TransactionStatement => "DSCSA_TS",
+        }
+    }
+
+    /// transaction_documents.document_kind
+    pub fn vault_kind(&self) -> &'static str {
+        match self {
+            DscsaDocumentKind::TransactionInformation => "dscsa_ti",
+            DscsaDocumentKind::TransactionHistory => "dscsa_th",
+            DscsaDocumentKind::TransactionStatement => "dscsa_ts",
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            DscsaDocumentKind::TransactionInformation => "Transaction Information",
+            DscsaDocumentKind::TransactionHistory => "Transaction History",
+            DscsaDocumentKind::TransactionStatement => "Transaction Statement",
+        }
+    }
+}
+
+// ============================================================================
+// SOURCE DATA
+// ============================================================================
+
+/// A marketplace transaction with the catalog and lot data of the product sold
+#[derive(Debug, Clone, FromRow)]
+pub struct DscsaTransactionLot {
+    pub transaction_id: Uuid,
+    pub transaction_date: Option<DateTime<Utc>>,
+    pub transaction_status: Option<String>,
+    pub quantity: i32,
+    pub seller_id: Uuid,
+    pub buyer_id: Uuid,
+    pub seller_country: Option<String>,
+    pub buyer_country: Option<String>,
+    pub inventory_id: Uuid,
+    pub pharmaceutical_id: Uuid,
+    pub batch_number: String,
+    pub expiry_date: NaiveDate,
+    pub brand_name: String,
+    pub generic_name: String,
+    pub ndc_code: Option<String>,
+    pub manufacturer: String,
+    pub strength: Option<String>,
+    pub dosage_form: Option<String>,
+}
+
+/// Name, address and license of a trading partner as printed on the documents
+#[derive(Debug, Clone, Serialize)]
+pub struct DscsaTradingPartner {
+    pub company_name: String,
+    pub address: Option<String>,
+    pub license_number: Option<String>,
+}
+
+/// Earlier platform sale of the same lot, newest owner last
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct DscsaPriorTransaction {
+    pub transaction_id: Uuid,
+    pub transaction_date: Option<DateTime<Utc>>,
+    pub seller_company: String,
+    pub buyer_company: String,
+    pub quantity: i32,
+}
+
+// ============================================================================
+// RESPONSE MODELS
+// ============================================================================
+
+/// A DSCSA document in a transaction's vault
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct DscsaDocument {
+    pub id: Uuid,
+    pub transaction_document_id: Uuid,
+    pub document_kind: String,
+    pub document_type: String,
+    pub document_number: String,
+    pub title: String,
+    pub status: String,
+    pub content: serde_json::Value,
+    pub content_hash: String,
+    pub generated_signature: Option<String>,
+    pub approved_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DscsaDocumentSet {
+    pub transaction_id: Uuid,
+    pub documents: Vec<DscsaDocument>,
+    /// False when the documents already existed
+    pub generated: bool,
+}
+
+// ============================================================================
+// DOCUMENT CONTENT
+// ============================================================================
+
+fn product_json(lot: &DscsaTransactionLot) -> serde_json::Value {
+    json!({
+        "proprietary_name": lot.brand_name,
+        "established_name": lot.generic_name,
+        "ndc": lot.ndc_code,
+        "strength": lot.strength,
+        "dosage_form": lot.dosage_form,
+        "manufacturer": lot.manufacturer,
+        "lot_number": lot.batch_number,
+        "expiration_date": lot.expiry_date,
+    })
+}
+
+fn transfer_json(
+    transaction_id: Uuid,
+    transaction_date: Option<DateTime<Utc>>,
+    quantity: i32,
+    seller: serde_json::Value,
+    buyer: serde_json::Value,
+) -> serde_json::Value {
+    json!({
+        "transaction_id": transaction_id,
+        "transaction_date": transaction_date,
+        "quantity": quantity,
+        "seller": seller,
+        "buyer": buyer,
+    })
+}
+
+fn header(kind: DscsaDocumentKind, lot: &DscsaTransactionLot) -> serde_json::Value {
+    json!({
+        "document": kind.title(),
+        "standard": DSCSA_STANDARD,
+        "transaction_id": lot.transaction_id,
+    })
+}
+
+/// Transaction Information: product, lot, quantity, date and the two trading partners
+pub fn build_transaction_information(
+    lot: &DscsaTransactionLot,
+    seller: &DscsaTradingPartner,
+    buyer: &DscsaTradingPartner,
+) -> serde_json::Value {
+    json!({
+        "header": header(DscsaDocumentKind::TransactionInformation, lot),
+        "product": product_json(lot),
+        "transaction": transfer_json(lot.transaction_id, lot.transaction_date, lot.quantity, json!(seller), json!(buyer)),
+        // Shipped with the transfer; a later shipment date is added by the seller
+        "shipment_date": serde_json::Value::Null,
+    })
+}
+
+/// Transaction History: the TI of each earlier platform sale of the lot, then this sale
+pub fn build_transaction_history(
+    lot: &DscsaTransactionLot,
+    seller: &DscsaTradingPartner,
+    buyer: &DscsaTradingPartner,
+    prior: &[DscsaPriorTransaction],
+) -> serde_json::Value {
+    let mut transactions: Vec<serde_json::Value> = prior
+        .iter()
+        .map(|t| {
+            transfer_json(
+                t.transaction_id,
+                t.transaction_date,
+                t.quantity,
+                json!({ "company_name": t.seller_company }),
+                json!({ "company_name": t.buyer_company }),
+            )
+        })
+        .collect();
+    transactions.push(transfer_json(
+        lot.transaction_id,
+        lot.transaction_date,
+        lot.quantity,
+        json!(seller),
+        json!(buyer),
+    ));
+
+    json!({
+        "header": header(DscsaDocumentKind::TransactionHistory, lot),
+        "product": product_json(lot),
+        // The seller's own acquisition from outside the platform is not recorded here
+        "acquired_outside_platform": prior.is_empty(),
+        "transactions": transactions,
+    })
+}
+
+/// Transaction Statement: the seller's §581(27) attestations
+pub fn build_transaction_statement(
+    lot: &DscsaTransactionLot,
+    seller: &DscsaTradingPartner,
+    buyer: &DscsaTradingPartner,
+) -> serde_json::Value {
+    json!({
+        "header": header(DscsaDocumentKind::TransactionStatement, lot),
+        "product": product_json(lot),
+        "transaction": transfer_json(lot.transaction_id, lot.transaction_date, lot.quantity, json!(seller), json!(buyer)),
+        "statement": format!("{}, as the entity transferring ownership in this transaction:", seller.company_name),
+        "attestations": TRANSACTION_STATEMENT,
+    })
+}
+
+/// Reason the transaction does not get DSCSA documents, if any
+pub fn dscsa_ineligibility(lot: &DscsaTransactionLot) -> Option<String> {
+    let is_us = |country: &Option<String>| country.as_deref() == Some(DSCSA_COUNTRY);
+    if !is_us(&lot.seller_country) || !is_us(&lot.buyer_country) {
+        return Some("DSCSA transaction data applies to sales between US trading partners (set the account country to US)".to_string());
+    }
+    if lot.ndc_code.as_deref().map_or(true, |ndc| ndc.trim().is_empty()) {
+        return Some("The product has no NDC, which DSCSA transaction information requires".to_string());
+    }
+    if lot.transaction_status.as_deref() == Some("cancelled") {
+        return Some("The transaction is cancelled".to_string());
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lot() -> DscsaTransactionLot {
+        DscsaTransactionLot {
+            transaction_id: Uuid::new_v4(),
+            transaction_date: Some(Utc::now()),
+            transaction_status: Some("pending".to_string()),
+            quantity: 40,
+            seller_id: Uuid::new_v4(),
+            buyer_id: Uuid::new_v4(),
+            seller_country: Some("US".to_string()),
+            buyer_country: Some("US".to_string()),
+            inventory_id: Uuid::new_v4(),
+            pharmaceutical_id: Uuid::new_v4(),
+            batch_number: "LOT-42A".to_string(),
+            expiry_date: NaiveDate::from_ymd_opt(2027, 3, 31).unwrap(),
+            brand_name: "Lipitor".to_string(),
+            generic_name: "atorvastatin calcium".to_string(),
+            ndc_code: Some("0071-0155-23".to_string()),
+            manufacturer: "Pfizer".to_string(),
+            strength: Some("20 mg".to_string()),
+            dosage_form: Some("TABLET".to_string()),
+        }
+    }
+
+    fn partner(name: &str) -> DscsaTradingPartner {
+        DscsaTradingPartner {
+            company_name: name.to_string(),
+            address: Some("1 Main St, Newark, NJ".to_string()),
+            license_number: Some("WD-1234".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_transaction_information() {
+        let lot = lot();
+        let ti = build_transaction_information(&lot, &partner("Seller Co"), &partner("Buyer Co"));
+
+        assert_eq!(ti["product"]["ndc"], "0071-0155-23");
+        assert_eq!(ti["product"]["lot_number"], "LOT-42A");
+        assert_eq!(ti["product"]["expiration_date"], "2027-03-31");
+        assert_eq!(ti["transaction"]["quantity"], 40);
+        assert_eq!(ti["transaction"]["seller"]["company_name"], "Seller Co");
+        assert_eq!(ti["transaction"]["buyer"]["license_number"], "WD-1234");
+    }
+
+    #[test]
+    fn test_transaction_history_ends_with_this_sale() {
+        let lot = lot();
+        let prior = vec![DscsaPriorTransaction {
+            transaction_id: Uuid::new_v4(),
+            transaction_date: Some(Utc::now()),
+            seller_company: "Distributor".to_string(),
+            buyer_company: "Seller Co".to_string(),
+            quantity: 100,
+        }];
+
+        let th = build_transaction_history(&lot, &partner("Seller Co"), &partner("Buyer Co"), &prior);
+        let transactions = th["transactions"].as_array().unwrap();
+        assert_eq!(transactions.len(), 2);
+        assert_eq!(transactions[0]["seller"]["company_name"], "Distributor");
+        assert_eq!(transactions[1]["transaction_id"], json!(lot.transaction_id));
+        assert_eq!(th["acquired_outside_platform"], false);
+
+        let th = build_transaction_history(&lot, &partner("Seller Co"), &partner("Buyer Co"), &[]);
+        assert_eq!(th["transactions"].as_array().unwrap().len(), 1);
+        assert_eq!(th["acquired_outside_platform"], true);
+    }
+
+    #[test]
+    fn test_transaction_statement() {
+        let ts = build_transaction_statement(&lot(), &partner("Seller Co"), &partner("Buyer Co"));
+        assert_eq!(ts["attestations"].as_array().unwrap().len(), TRANSACTION_STATEMENT.len());
+        assert!(ts["statement"].as_str().unwrap().starts_with("Seller Co"));
+    }
+
+    #[test]
+    fn test_dscsa_ineligibility() {
+        assert!(dscsa_ineligibility(&lot()).is_none());
+
+        let mut foreign = lot();
+        foreign.buyer_country = Some("DE".to_string());
+        assert!(dscsa_ineligibility(&foreign).is_some());
+
+        let mut unknown = lot();
+        unknown.seller_country = None;
+        assert!(dscsa_ineligibility(&unknown).is_some());
+
+        let mut no_ndc = lot();
+        no_ndc.ndc_code = Some(" ".to_string());
+        assert!(dscsa_ineligibility(&no_ndc).is_some());
+    }
+}
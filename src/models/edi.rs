@@ -0,0 +1,347 @@
+/// EDI (ANSI X12) exchange models
+///
+/// A marketplace user whose ERP trades over EDI registers one trading partner profile.
+/// Purchase orders (850) are emitted to the seller's partner for marketplace
+/// transactions; acknowledgments (855), ship notices (856) and invoices (810) come back
+/// and are translated into the structures below before being applied.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+// ============================================================================
+// ENUMS
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum EdiTransport {
+    As2,
+    Sftp,
+}
+
+impl EdiTransport {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EdiTransport::As2 => "as2",
+            EdiTransport::Sftp => "sftp",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "as2" => Some(EdiTransport::As2),
+            "sftp" => Some(EdiTransport::Sftp),
+            _ => None,
+        }
+    }
+}
+
+/// Supported X12 transaction sets
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum EdiTransactionSet {
+    #[serde(rename = "850")]
+    PurchaseOrder,
+    #[serde(rename = "855")]
+    PurchaseOrderAcknowledgment,
+    #[serde(rename = "856")]
+    ShipNotice,
+    #[serde(rename = "810")]
+    Invoice,
+}
+
+impl EdiTransactionSet {
+    pub fn code(&self) -> &'static str {
+        match self {
+            EdiTransactionSet::PurchaseOrder => "850",
+            EdiTransactionSet::PurchaseOrderAcknowledgment => "855",
+            EdiTransactionSet::ShipNotice => "856",
+            EdiTransactionSet::Invoice => "810",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "850" => Some(EdiTransactionSet::PurchaseOrder),
+            "855" => Some(EdiTransactionSet::PurchaseOrderAcknowledgment),
+            "856" => Some(EdiTransactionSet::ShipNotice),
+            "810" => Some(EdiTransactionSet::Invoice),
+            _ => None,
+        }
+    }
+
+    /// GS01 functional identifier code
+    pub fn functional_id(&self) -> &'static str {
+        match self {
+            EdiTransactionSet::PurchaseOrder => "PO",
+            EdiTransactionSet::PurchaseOrderAcknowledgment => "PR",
+            EdiTransactionSet::ShipNotice => "SH",
+            EdiTransactionSet::Invoice => "IN",
+        }
+    }
+}
+
+// ============================================================================
+// TRADING PARTNERS
+// ============================================================================
+
+#[derive(Debug, Clone, FromRow)]
+pub struct EdiTradingPartner {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub isa_qualifier: String,
+    pub isa_id: String,
+    pub gs_id: String,
+    pub x12_version: String,
+    pub test_mode: bool,
+    pub transport: String,
+    pub as2_id: Option<String>,
+    pub as2_url: Option<String>,
+    pub as2_username: Option<String>,     // Encrypted
+    pub as2_password: Option<String>,     // Encrypted
+    pub as2_inbound_token: Option<String>, // Encrypted
+    pub sftp_host: Option<String>,
+    pub sftp_port: Option<i32>,
+    pub sftp_username: Option<String>,    // Encrypted
+    pub sftp_password: Option<String>,    // Encrypted
+    pub sftp_private_key: Option<String>, // Encrypted
+    pub sftp_host_key_fingerprint: Option<String>,
+    pub sftp_inbound_path: Option<String>,
+    pub sftp_outbound_path: Option<String>,
+    pub sftp_archive_path: Option<String>,
+    pub enabled: bool,
+    pub last_polled_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Create or replace the caller's partner profile. Omitted secrets keep their stored value.
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct UpsertEdiPartnerRequest {
+    #[validate(length(min = 1, max = 255))]
+    pub name: String,
+    #[validate(length(equal = 2, message = "ISA qualifier must be 2 characters"))]
+    pub isa_qualifier: Option<String>,
+    #[validate(length(min = 1, max = 15, message = "ISA id must be 1-15 characters"))]
+    pub isa_id: String,
+    #[validate(length(min = 2, max = 15, message = "GS id must be 2-15 characters"))]
+    pub gs_id: Option<String>,
+    pub x12_version: Option<String>,
+    pub test_mode: Option<bool>,
+    pub transport: EdiTransport,
+    pub enabled: Option<bool>,
+
+    // AS2
+    #[validate(length(min = 1, max = 128))]
+    pub as2_id: Option<String>,
+    #[validate(url)]
+    pub as2_url: Option<String>,
+    pub as2_username: Option<String>,
+    pub as2_password: Option<String>,
+    /// Rotates the token the partner uses to post to Atlas (generated on first save)
+    #[serde(default)]
+    pub rotate_inbound_token: bool,
+
+    // SFTP
+    pub sftp_host: Option<String>,
+    #[validate(range(min = 1, max = 65535))]
+    pub sftp_port: Option<i32>,
+    pub sftp_username: Option<String>,
+    pub sftp_password: Option<String>,
+    pub sftp_private_key: Option<String>,
+    pub sftp_host_key_fingerprint: Option<String>,
+    pub sftp_inbound_path: Option<String>,
+    pub sftp_outbound_path: Option<String>,
+    pub sftp_archive_path: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct EdiPartnerResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub isa_qualifier: String,
+    pub isa_id: String,
+    pub gs_id: String,
+    pub x12_version: String,
+    pub test_mode: bool,
+    pub transport: String,
+    pub enabled: bool,
+    pub as2_id: Option<String>,
+    pub as2_url: Option<String>,
+    /// Where the partner posts AS2 messages for Atlas
+    pub as2_inbound_path: String,
+    /// Returned only when the token is generated or rotated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub as2_inbound_token: Option<String>,
+    pub sftp_host: Option<String>,
+    pub sftp_port: Option<i32>,
+    pub sftp_inbound_path: Option<String>,
+    pub sftp_outbound_path: Option<String>,
+    pub sftp_archive_path: Option<String>,
+    pub has_sftp_credentials: bool,
+    pub last_polled_at: Option<DateTime<Utc>>,
+    /// Atlas' own interchange identification, for the partner's configuration
+    pub atlas_isa_qualifier: String,
+    pub atlas_isa_id: String,
+    pub atlas_gs_id: String,
+    pub atlas_as2_id: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+// ============================================================================
+// EXCHANGED DOCUMENTS
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct EdiDocument {
+    pub id: Uuid,
+    pub partner_id: Uuid,
+    pub transaction_id: Option<Uuid>,
+    pub direction: String,
+    pub transaction_set: String,
+    pub interchange_control_number: String,
+    pub transaction_control_number: String,
+    pub po_number: Option<String>,
+    pub transport: String,
+    pub status: String,
+    #[serde(skip_serializing)]
+    pub raw_x12: String,
+    pub translated: serde_json::Value,
+    pub notes: Option<String>,
+    pub error_message: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EdiDocumentQuery {
+    pub transaction_id: Option<Uuid>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+// ============================================================================
+// TRANSLATED TRANSACTION SETS
+// ============================================================================
+
+/// 850 - built from a marketplace transaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EdiPurchaseOrder {
+    pub po_number: String,
+    pub po_date: NaiveDate,
+    pub buyer_name: String,
+    pub seller_name: String,
+    pub currency: String,
+    pub lines: Vec<EdiOrderLine>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EdiOrderLine {
+    pub line_number: u32,
+    pub quantity: i32,
+    pub unit_price: Decimal,
+    /// Product ID qualifier: "N4" (11-digit NDC) or "VP" (vendor part number)
+    pub product_id_qualifier: String,
+    pub product_id: String,
+    pub lot_number: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EdiAckStatus {
+    Accepted,
+    AcceptedWithChanges,
+    Rejected,
+}
+
+/// 855 - the seller's response to a purchase order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EdiAcknowledgment {
+    pub po_number: String,
+    pub status: EdiAckStatus,
+    /// BAK02 as received
+    pub status_code: String,
+    pub ack_date: Option<NaiveDate>,
+    pub lines: Vec<EdiAckLine>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EdiAckLine {
+    pub line_number: Option<String>,
+    /// ACK01 line status, e.g. "IA" accepted, "IR" rejected, "IQ" quantity changed
+    pub status_code: String,
+    pub quantity: Option<i32>,
+    pub product_id: Option<String>,
+}
+
+/// 856 - what was shipped against one or more purchase orders
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EdiShipNotice {
+    pub shipment_id: String,
+    pub ship_date: Option<NaiveDate>,
+    pub po_numbers: Vec<String>,
+    pub carrier: Option<String>,
+    pub tracking_number: Option<String>,
+    pub lines: Vec<EdiShipLine>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EdiShipLine {
+    pub product_id: Option<String>,
+    pub lot_number: Option<String>,
+    pub expiry_date: Option<NaiveDate>,
+    pub quantity: Option<i32>,
+}
+
+/// 810 - the seller's invoice for a purchase order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EdiInvoice {
+    pub invoice_number: String,
+    pub invoice_date: Option<NaiveDate>,
+    pub po_number: String,
+    pub total_amount: Decimal,
+    pub lines: Vec<EdiInvoiceLine>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EdiInvoiceLine {
+    pub line_number: Option<String>,
+    pub quantity: i32,
+    pub unit_price: Decimal,
+    pub product_id: Option<String>,
+}
+
+/// A received transaction set after translation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EdiInboundDocument {
+    Acknowledgment(EdiAcknowledgment),
+    ShipNotice(EdiShipNotice),
+    Invoice(EdiInvoice),
+}
+
+impl EdiInboundDocument {
+    pub fn transaction_set(&self) -> EdiTransactionSet {
+        match self {
+            EdiInboundDocument::Acknowledgment(_) => EdiTransactionSet::PurchaseOrderAcknowledgment,
+            EdiInboundDocument::ShipNotice(_) => EdiTransactionSet::ShipNotice,
+            EdiInboundDocument::Invoice(_) => EdiTransactionSet::Invoice,
+        }
+    }
+
+    /// The purchase order the document refers to (the first one for a multi-order 856)
+    pub fn po_number(&self) -> Option<&str> {
+        match self {
+            EdiInboundDocument::Acknowledgment(ack) => Some(&ack.po_number),
+            EdiInboundDocument::ShipNotice(asn) => asn.po_numbers.first().map(String::as_str),
+            EdiInboundDocument::Invoice(invoice) => Some(&invoice.po_number),
+        }
+    }
+}
@@ -0,0 +1,231 @@
+/// 21 CFR Part 11 electronic signature models: signing sequences and signature
+/// manifests of regulatory documents
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use sha2::{Digest, Sha256};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SignatureMeaning {
+    Authorship,
+    Review,
+    Approval,
+    Responsibility,
+}
+
+impl SignatureMeaning {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SignatureMeaning::Authorship => "authorship",
+            SignatureMeaning::Review => "review",
+            SignatureMeaning::Approval => "approval",
+            SignatureMeaning::Responsibility => "responsibility",
+        }
+    }
+
+    /// Statement the signer attests to, recorded in the manifest
+    pub fn statement(&self) -> &'static str {
+        match self {
+            SignatureMeaning::Authorship => "I am the author of this document",
+            SignatureMeaning::Review => "I have reviewed this document",
+            SignatureMeaning::Approval => "I approve this document",
+            SignatureMeaning::Responsibility => "I take responsibility for this document",
+        }
+    }
+}
+
+// ============================================================================
+// DATABASE MODELS
+// ============================================================================
+
+/// A slot in a document's signing sequence; signed slots carry the manifest
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct DocumentSignature {
+    pub id: Uuid,
+    pub document_id: Uuid,
+    pub sequence_number: i32,
+    pub signer_id: Uuid,
+    pub meaning: String,
+    pub status: String,
+    pub signer_name: Option<String>,
+    pub signed_at: Option<DateTime<Utc>>,
+    pub content_hash: Option<String>,
+    pub manifest: Option<serde_json::Value>,
+    pub manifest_hash: Option<String>,
+    pub signature: Option<String>,
+    pub signature_public_key: Option<String>,
+    pub auth_method: Option<String>,
+    pub requested_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl DocumentSignature {
+    pub fn is_signed(&self) -> bool {
+        self.status == "signed"
+    }
+}
+
+// ============================================================================
+// REQUEST / RESPONSE MODELS
+// ============================================================================
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct SignerSlot {
+    pub user_id: Uuid,
+    pub meaning: SignatureMeaning,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct SetSigningSequenceRequest {
+    /// Signers in signing order
+    #[validate(length(min = 1, max = 10, message = "A signing sequence has 1-10 signers"))]
+    pub signers: Vec<SignerSlot>,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct SignDocumentRequest {
+    /// Re-authentication at signing
+    #[validate(length(min = 1, message = "Password is required"))]
+    pub password: String,
+    /// Required when the signer has MFA enabled
+    pub totp_code: Option<String>,
+    /// Must match the meaning of the signer's slot
+    pub meaning: SignatureMeaning,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SigningStatus {
+    pub document_id: Uuid,
+    pub document_status: String,
+    pub signatures: Vec<DocumentSignature>,
+    /// Next signer in the sequence, if any
+    pub next_signer_id: Option<Uuid>,
+}
+
+/// Result of re-checking every signed manifest of a document
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SignatureVerification {
+    pub signature_count: usize,
+    pub valid: bool,
+    pub invalid_signature_ids: Vec<Uuid>,
+}
+
+// ============================================================================
+// MANIFEST
+// ============================================================================
+
+/// What a signer signs: who, in what meaning, when, and over which document content
+#[derive(Debug, Clone, Serialize)]
+pub struct SignatureManifest {
+    pub document_id: Uuid,
+    pub document_number: String,
+    pub content_hash: String,
+    pub sequence_number: i32,
+    pub signer_id: Uuid,
+    pub signer_name: String,
+    pub meaning: SignatureMeaning,
+    pub statement: String,
+    pub signed_at: DateTime<Utc>,
+}
+
+impl SignatureManifest {
+    pub fn to_value(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+}
+
+/// Canonical text of a manifest (object keys sorted, as stored in JSONB) and its
+/// SHA-256; the signature is over this hash
+pub fn manifest_text(manifest: &serde_json::Value) -> String {
+    manifest.to_string()
+}
+
+pub fn manifest_hash(manifest: &serde_json::Value) -> String {
+    hex::encode(Sha256::digest(manifest_text(manifest).as_bytes()))
+}
+
+/// First unsigned slot of a sequence ordered by sequence_number
+pub fn next_pending_signature(signatures: &[DocumentSignature]) -> Option<&DocumentSignature> {
+    signatures.iter().find(|s| !s.is_signed())
+}
+
+/// Check a signed slot against the document as stored: the manifest hashes to the
+/// recorded hash and covers the current content. The Ed25519 signature is checked
+/// separately.
+pub fn manifest_matches(signature: &DocumentSignature, document_content_hash: &str) -> bool {
+    let Some(manifest) = &signature.manifest else {
+        return false;
+    };
+    signature.manifest_hash.as_deref() == Some(manifest_hash(manifest).as_str())
+        && manifest.get("content_hash").and_then(|h| h.as_str()) == Some(document_content_hash)
+        && signature.content_hash.as_deref() == Some(document_content_hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slot(sequence_number: i32, status: &str) -> DocumentSignature {
+        DocumentSignature {
+            id: Uuid::new_v4(),
+            document_id: Uuid::new_v4(),
+            sequence_number,
+            signer_id: Uuid::new_v4(),
+            meaning: "review".to_string(),
+            status: status.to_string(),
+            signer_name: None,
+            signed_at: None,
+            content_hash: None,
+            manifest: None,
+            manifest_hash: None,
+            signature: None,
+            signature_public_key: None,
+            auth_method: None,
+            requested_by: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_next_pending_signature() {
+        let sequence = vec![slot(1, "signed"), slot(2, "pending"), slot(3, "pending")];
+        assert_eq!(next_pending_signature(&sequence).unwrap().sequence_number, 2);
+
+        let done = vec![slot(1, "signed")];
+        assert!(next_pending_signature(&done).is_none());
+    }
+
+    #[test]
+    fn test_manifest_hash_survives_storage_round_trip() {
+        let manifest = SignatureManifest {
+            document_id: Uuid::new_v4(),
+            document_number: "CoA-2026-000001".to_string(),
+            content_hash: "ab".repeat(32),
+            sequence_number: 1,
+            signer_id: Uuid::new_v4(),
+            signer_name: "Jane Doe".to_string(),
+            meaning: SignatureMeaning::Approval,
+            statement: SignatureMeaning::Approval.statement().to_string(),
+            signed_at: Utc::now(),
+        }
+        .to_value();
+
+        let stored: serde_json::Value = serde_json::from_str(&manifest.to_string()).unwrap();
+        assert_eq!(manifest_hash(&manifest), manifest_hash(&stored));
+
+        let mut signed = slot(1, "signed");
+        signed.manifest = Some(stored);
+        signed.manifest_hash = Some(manifest_hash(&manifest));
+        signed.content_hash = Some("ab".repeat(32));
+        assert!(manifest_matches(&signed, &"ab".repeat(32)));
+        assert!(!manifest_matches(&signed, &"cd".repeat(32)));
+
+        signed.manifest.as_mut().unwrap()["signer_name"] = serde_json::json!("Someone Else");
+        assert!(!manifest_matches(&signed, &"ab".repeat(32)));
+    }
+}
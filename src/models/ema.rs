@@ -1,7 +1,9 @@
 use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 use sqlx::FromRow;
 use uuid::Uuid;
+use crate::models::ingredient::{IngredientRole, ParsedIngredient};
 
 // ============================================================================
 // EMA ePI API Response Models (FHIR Bundle Structure)
@@ -272,7 +274,7 @@ pub struct EmaCatalogEntry {
 }
 
 /// Simplified response model for client API
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, ToSchema)]
 pub struct EmaCatalogResponse {
     pub id: Uuid,
     pub eu_number: String,
@@ -289,7 +291,8 @@ pub struct EmaCatalogResponse {
 }
 
 /// Search request parameters
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct EmaSearchRequest {
     pub query: Option<String>,
     pub language: Option<String>,
@@ -297,12 +300,14 @@ pub struct EmaSearchRequest {
     pub therapeutic_area: Option<String>,
     pub atc_code: Option<String>,
     pub mah_name: Option<String>,
+    /// Comma-separated fields to count values of, e.g. `therapeutic_area,pharmaceutical_form`
+    pub facets: Option<String>,
     pub limit: Option<i64>,
     pub offset: Option<i64>,
 }
 
 /// Sync log entry for tracking synchronization operations
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct EmaSyncLog {
     pub id: Uuid,
     pub sync_started_at: DateTime<Utc>,
@@ -321,10 +326,99 @@ pub struct EmaSyncLog {
     pub api_response_time_ms: Option<i32>,
     pub processing_time_ms: Option<i32>,
     pub created_at: DateTime<Utc>,
+    pub total_expected: Option<i32>,
+    pub records_processed: Option<i32>,
+    pub current_batch: Option<i32>,
+    pub total_batches: Option<i32>,
+    pub last_heartbeat_at: Option<DateTime<Utc>>,
+    pub cancelled_at: Option<DateTime<Utc>>,
+    pub cancelled_by: Option<Uuid>,
+    /// Set when guardrails marked the run `suspect`
+    pub anomalies: Option<serde_json::Value>,
+}
+
+impl EmaSyncLog {
+    /// In-progress syncs are considered interrupted after this long without a heartbeat
+    pub const STALE_AFTER_MINUTES: i64 = 10;
+
+    /// True when the sync is still marked in_progress but its worker stopped reporting
+    pub fn is_stale(&self) -> bool {
+        let last_seen = self.last_heartbeat_at.unwrap_or(self.sync_started_at);
+        self.status == "in_progress"
+            && Utc::now() - last_seen > chrono::Duration::minutes(Self::STALE_AFTER_MINUTES)
+    }
+}
+
+/// Sync progress for polling clients
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct EmaSyncProgressResponse {
+    pub id: Uuid,
+    pub status: String,
+    pub sync_type: Option<String>,
+    pub language_code: Option<String>,
+    pub progress_percent: f64,
+    pub records_processed: i32,
+    pub total_expected: i32,
+    pub records_inserted: i32,
+    pub records_updated: i32,
+    pub records_skipped: i32,
+    pub records_failed: i32,
+    pub current_batch: i32,
+    pub total_batches: i32,
+    pub elapsed_seconds: i64,
+    pub estimated_remaining_seconds: Option<i64>,
+    pub error_message: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    /// In-progress runs whose heartbeat stopped (e.g. the server restarted mid-sync)
+    pub interrupted: bool,
+}
+
+impl From<EmaSyncLog> for EmaSyncProgressResponse {
+    fn from(log: EmaSyncLog) -> Self {
+        let total_expected = log.total_expected.unwrap_or(0);
+        let records_processed = log.records_processed.unwrap_or(0);
+        let progress_percent = if total_expected > 0 {
+            (records_processed as f64 / total_expected as f64 * 100.0).min(100.0)
+        } else {
+            0.0
+        };
+
+        let elapsed_seconds = (log.sync_completed_at.unwrap_or_else(Utc::now) - log.sync_started_at).num_seconds();
+        let estimated_remaining = if progress_percent > 0.0 && log.status == "in_progress" {
+            let total_estimated = (elapsed_seconds as f64 / progress_percent) * 100.0;
+            Some((total_estimated - elapsed_seconds as f64) as i64)
+        } else {
+            None
+        };
+        let interrupted = log.is_stale();
+
+        Self {
+            id: log.id,
+            status: log.status,
+            sync_type: log.sync_type,
+            language_code: log.language_code,
+            progress_percent,
+            records_processed,
+            total_expected,
+            records_inserted: log.records_inserted.unwrap_or(0),
+            records_updated: log.records_updated.unwrap_or(0),
+            records_skipped: log.records_skipped.unwrap_or(0),
+            records_failed: log.records_failed.unwrap_or(0),
+            current_batch: log.current_batch.unwrap_or(0),
+            total_batches: log.total_batches.unwrap_or(0),
+            elapsed_seconds,
+            estimated_remaining_seconds: estimated_remaining,
+            error_message: log.error_message,
+            started_at: log.sync_started_at,
+            completed_at: log.sync_completed_at,
+            interrupted,
+        }
+    }
 }
 
 /// Statistics about the catalog
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, ToSchema)]
 pub struct EmaCatalogStats {
     pub total_entries: i64,
     pub entries_by_language: Vec<LanguageCount>,
@@ -336,26 +430,97 @@ pub struct EmaCatalogStats {
 }
 
 /// Count by language
-#[derive(Debug, Serialize, Clone, FromRow)]
+#[derive(Debug, Serialize, Clone, FromRow, ToSchema)]
 pub struct LanguageCount {
     pub language_code: String,
     pub count: i64,
 }
 
 /// Count by authorization status
-#[derive(Debug, Serialize, Clone, FromRow)]
+#[derive(Debug, Serialize, Clone, FromRow, ToSchema)]
 pub struct StatusCount {
     pub status: String,
     pub count: i64,
 }
 
 /// Count by therapeutic area
-#[derive(Debug, Serialize, Clone, FromRow)]
+#[derive(Debug, Serialize, Clone, FromRow, ToSchema)]
 pub struct TherapeuticAreaCount {
     pub therapeutic_area: String,
     pub count: i64,
 }
 
+// ============================================================================
+// Product Information Documents
+// ============================================================================
+
+/// Product information document kinds referenced by a catalog entry
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum EmaDocumentType {
+    Epi,
+    Smpc,
+    Pil,
+}
+
+impl EmaDocumentType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EmaDocumentType::Epi => "epi",
+            EmaDocumentType::Smpc => "smpc",
+            EmaDocumentType::Pil => "pil",
+        }
+    }
+}
+
+/// Downloaded document (content lives encrypted on disk at `storage_path`)
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct EmaDocument {
+    pub id: Uuid,
+    pub eu_number: String,
+    pub language_code: String,
+    pub document_type: String,
+    pub source_url: String,
+    #[serde(skip_serializing)]
+    pub storage_path: String,
+    pub content_hash: String,
+    pub content_type: Option<String>,
+    pub size_bytes: i64,
+    pub extracted_text: Option<String>,
+    pub fetched_at: DateTime<Utc>,
+    pub changed_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EmaDocumentQuery {
+    pub language: Option<String>,
+    /// Include the extracted text (can be large)
+    pub include_text: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EmaDocumentSearchRequest {
+    pub q: String,
+    pub language: Option<String>,
+    pub document_type: Option<EmaDocumentType>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// Full-text match in a document, with a highlighted excerpt
+#[derive(Debug, Serialize, FromRow, ToSchema)]
+pub struct EmaDocumentSearchResult {
+    pub eu_number: String,
+    pub product_name: Option<String>,
+    pub language_code: String,
+    pub document_type: String,
+    pub excerpt: String,
+    pub rank: f32,
+}
+
 // ============================================================================
 // Conversion Implementations
 // ============================================================================
@@ -402,8 +567,8 @@ impl EpiEntry {
             pharmaceutical_form: None, // Would need deeper parsing
             route_of_administration: None, // Would need deeper parsing
             strength: None, // Would need deeper parsing
-            active_substances: None, // Would need deeper parsing from contained resources
-            excipients: None, // Would need deeper parsing
+            active_substances: self.extract_ingredients(IngredientRole::Active),
+            excipients: self.extract_ingredients(IngredientRole::Inactive),
             atc_code: self.extract_atc_code(),
             atc_classification: None, // Would need deeper parsing
             therapeutic_area: self.extract_therapeutic_area(),
@@ -625,6 +790,54 @@ impl EpiEntry {
         None
     }
 
+    /// Extract active substances or excipients from contained FHIR `Ingredient` resources
+    ///
+    /// Stored as `[{"name": ..., "strength": ..., "allergens": [...]}]`; None when the bundle has no
+    /// ingredient data for that role, so an unknown excipient list is never mistaken for an empty one.
+    fn extract_ingredients(&self, role: IngredientRole) -> Option<serde_json::Value> {
+        let contained = self.resource.contained.as_ref()?;
+
+        let ingredients: Vec<ParsedIngredient> = contained
+            .iter()
+            .filter(|r| r.get("resourceType").and_then(|t| t.as_str()) == Some("Ingredient"))
+            .filter(|r| {
+                let role_text = r.pointer("/role/coding/0/display")
+                    .or_else(|| r.pointer("/role/text"))
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("")
+                    .to_lowercase();
+                match role {
+                    IngredientRole::Active => role_text.contains("active"),
+                    IngredientRole::Inactive => role_text.contains("excipient") || role_text.contains("inactive"),
+                }
+            })
+            .filter_map(|r| {
+                let name = r.pointer("/substance/code/concept/coding/0/display")
+                    .or_else(|| r.pointer("/substance/code/concept/text"))
+                    .and_then(|v| v.as_str())?;
+                let strength = r.pointer("/substance/strength/0/textPresentation")
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string);
+                Some(ParsedIngredient::new(name, strength, role))
+            })
+            .collect();
+
+        if ingredients.is_empty() {
+            return None;
+        }
+
+        serde_json::to_value(
+            ingredients
+                .iter()
+                .map(|i| serde_json::json!({
+                    "name": i.name,
+                    "strength": i.strength,
+                    "allergens": i.allergens,
+                }))
+                .collect::<Vec<_>>()
+        ).ok()
+    }
+
     /// Helper to parse date strings
     fn extract_date(&self, date_str: Option<&str>) -> Option<NaiveDate> {
         date_str?.parse().ok()
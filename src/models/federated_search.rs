@@ -0,0 +1,280 @@
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use sqlx::FromRow;
+use crate::middleware::error_handling::{AppError, Result};
+
+/// Catalogs covered by federated search, in the order they are listed
+pub const FEDERATED_SOURCES: [&str; 5] = ["openfda", "ema", "mhra", "health_canada", "swissmedic"];
+
+/// Salt and hydrate words dropped when comparing ingredient names across catalogs
+const SALT_WORDS: &[&str] = &[
+    "HYDROCHLORIDE", "HCL", "HYDROBROMIDE", "SODIUM", "POTASSIUM", "CALCIUM", "MAGNESIUM",
+    "SULFATE", "SULPHATE", "MALEATE", "FUMARATE", "SUCCINATE", "TARTRATE", "BITARTRATE",
+    "CITRATE", "ACETATE", "PHOSPHATE", "MESYLATE", "BESYLATE", "BESILATE", "TOSYLATE",
+    "BROMIDE", "CHLORIDE", "MONOHYDRATE", "DIHYDRATE", "TRIHYDRATE", "HEMIHYDRATE", "ANHYDROUS",
+];
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FederatedSearchRequest {
+    /// Matches product name, ingredient and identifier (NDC, EU number, source id)
+    pub query: Option<String>,
+    pub ingredient: Option<String>,
+    /// ATC prefix; OpenFDA entries match through their RxNorm mapping
+    pub atc_code: Option<String>,
+    /// Comma-separated sources (default: all)
+    pub sources: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+impl FederatedSearchRequest {
+    pub fn query_text(&self) -> Option<&str> {
+        self.query.as_deref().map(str::trim).filter(|q| !q.is_empty())
+    }
+
+    pub fn ingredient_text(&self) -> Option<&str> {
+        self.ingredient.as_deref().map(str::trim).filter(|i| !i.is_empty())
+    }
+
+    pub fn atc_prefix(&self) -> Option<String> {
+        self.atc_code.as_deref().map(|a| a.trim().to_uppercase()).filter(|a| !a.is_empty())
+    }
+
+    /// Requested sources, validated and deduplicated in `FEDERATED_SOURCES` order
+    pub fn source_list(&self) -> Result<Vec<String>> {
+        let requested: Vec<String> = match self.sources.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+            None => return Ok(FEDERATED_SOURCES.iter().map(|s| s.to_string()).collect()),
+            Some(list) => list.split(',').map(|s| s.trim().to_lowercase().replace('-', "_")).collect(),
+        };
+
+        if let Some(unknown) = requested.iter().find(|s| !FEDERATED_SOURCES.contains(&s.as_str())) {
+            return Err(AppError::BadRequest(format!(
+                "Unknown catalog source '{}'. Available: {}",
+                unknown,
+                FEDERATED_SOURCES.join(", ")
+            )));
+        }
+
+        Ok(FEDERATED_SOURCES
+            .iter()
+            .filter(|s| requested.iter().any(|r| r == *s))
+            .map(|s| s.to_string())
+            .collect())
+    }
+}
+
+/// A product from any catalog, in a common shape
+#[derive(Debug, Clone, FromRow)]
+pub struct FederatedSearchRow {
+    pub source: String,
+    pub source_id: String,
+    pub country: String,
+    pub product_name: String,
+    pub active_ingredients: Vec<String>,
+    pub strength: Option<String>,
+    pub dosage_form: Option<String>,
+    pub manufacturer: Option<String>,
+    pub authorization_status: Option<String>,
+    /// Catalog ATC code, or the RxNorm-mapped code for OpenFDA entries
+    pub atc_code: Option<String>,
+    pub rxcuis: Vec<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FederatedSearchResult {
+    pub source: String,
+    pub source_id: String,
+    pub country: String,
+    pub product_name: String,
+    pub active_ingredients: Vec<String>,
+    pub strength: Option<String>,
+    pub dosage_form: Option<String>,
+    pub manufacturer: Option<String>,
+    pub authorization_status: Option<String>,
+    pub atc_code: Option<String>,
+    pub rxcuis: Vec<String>,
+    pub equivalence_key: Option<String>,
+}
+
+impl From<FederatedSearchRow> for FederatedSearchResult {
+    fn from(row: FederatedSearchRow) -> Self {
+        let equivalence_key = equivalence_key(row.atc_code.as_deref(), &row.active_ingredients);
+        Self {
+            source: row.source,
+            source_id: row.source_id,
+            country: row.country,
+            product_name: row.product_name,
+            active_ingredients: row.active_ingredients,
+            strength: row.strength,
+            dosage_form: row.dosage_form,
+            manufacturer: row.manufacturer,
+            authorization_status: row.authorization_status,
+            atc_code: row.atc_code,
+            rxcuis: row.rxcuis,
+            equivalence_key,
+        }
+    }
+}
+
+/// Products on the current page that are the same substance(s) in different catalogs
+#[derive(Debug, Serialize, ToSchema)]
+pub struct EquivalenceGroup {
+    pub equivalence_key: String,
+    pub atc_code: Option<String>,
+    pub sources: Vec<String>,
+    pub source_ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FederatedSearchResponse {
+    pub results: Vec<FederatedSearchResult>,
+    pub groups: Vec<EquivalenceGroup>,
+    pub sources: Vec<String>,
+    pub limit: i64,
+    pub offset: i64,
+    pub has_more: bool,
+}
+
+impl FederatedSearchResponse {
+    pub fn new(results: Vec<FederatedSearchResult>, sources: Vec<String>, limit: i64, offset: i64, has_more: bool) -> Self {
+        let groups = group_equivalents(&results);
+        Self { results, groups, sources, limit, offset, has_more }
+    }
+}
+
+/// Key shared by equivalent products: the substance-level (5th level, 7 character) ATC
+/// code when known, otherwise the sorted ingredient names without salts
+pub fn equivalence_key(atc_code: Option<&str>, ingredients: &[String]) -> Option<String> {
+    if let Some(atc) = atc_code.map(|a| a.trim().to_uppercase()).filter(|a| a.len() >= 7) {
+        return Some(format!("atc:{}", &atc[..7]));
+    }
+
+    let mut names: Vec<String> = ingredients
+        .iter()
+        .map(|i| normalize_ingredient(i))
+        .filter(|i| !i.is_empty())
+        .collect();
+    if names.is_empty() {
+        return None;
+    }
+    names.sort();
+    names.dedup();
+    Some(format!("ingredients:{}", names.join("+")))
+}
+
+/// "Metformin Hydrochloride 500 mg" and "METFORMIN HCL" both become "METFORMIN"
+pub fn normalize_ingredient(name: &str) -> String {
+    name.to_uppercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .filter(|w| !SALT_WORDS.contains(w))
+        .take_while(|w| !w.starts_with(|c: char| c.is_ascii_digit()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Groups with products from at least two sources; ATC groups absorb ingredient groups
+/// of the same substance so mapped and unmapped products still line up
+fn group_equivalents(results: &[FederatedSearchResult]) -> Vec<EquivalenceGroup> {
+    // Ingredient key -> ATC key, learned from products that have both
+    let mut atc_for_ingredients: BTreeMap<String, String> = BTreeMap::new();
+    for result in results {
+        if let (Some(atc_key), Some(ingredient_key)) = (
+            result.equivalence_key.as_ref().filter(|k| k.starts_with("atc:")),
+            equivalence_key(None, &result.active_ingredients),
+        ) {
+            atc_for_ingredients.entry(ingredient_key).or_insert_with(|| atc_key.clone());
+        }
+    }
+
+    let mut groups: BTreeMap<String, EquivalenceGroup> = BTreeMap::new();
+    for result in results {
+        let Some(key) = result.equivalence_key.as_ref() else { continue };
+        let key = atc_for_ingredients.get(key).unwrap_or(key).clone();
+
+        let group = groups.entry(key.clone()).or_insert_with(|| EquivalenceGroup {
+            atc_code: key.strip_prefix("atc:").map(str::to_string),
+            equivalence_key: key,
+            sources: Vec::new(),
+            source_ids: Vec::new(),
+        });
+        if !group.sources.contains(&result.source) {
+            group.sources.push(result.source.clone());
+        }
+        group.source_ids.push(format!("{}:{}", result.source, result.source_id));
+    }
+
+    groups.into_values().filter(|g| g.sources.len() > 1).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(source: &str, id: &str, atc: Option<&str>, ingredients: &[&str]) -> FederatedSearchResult {
+        FederatedSearchRow {
+            source: source.to_string(),
+            source_id: id.to_string(),
+            country: String::new(),
+            product_name: String::new(),
+            active_ingredients: ingredients.iter().map(|i| i.to_string()).collect(),
+            strength: None,
+            dosage_form: None,
+            manufacturer: None,
+            authorization_status: None,
+            atc_code: atc.map(str::to_string),
+            rxcuis: Vec::new(),
+        }
+        .into()
+    }
+
+    #[test]
+    fn test_equivalence_key() {
+        assert_eq!(equivalence_key(Some("a10ba02"), &[]).as_deref(), Some("atc:A10BA02"));
+        // Anatomical groups are too coarse to mean equivalence
+        assert_eq!(
+            equivalence_key(Some("A10B"), &["Metformin Hydrochloride".to_string()]).as_deref(),
+            Some("ingredients:METFORMIN")
+        );
+        assert_eq!(
+            equivalence_key(None, &["SITAGLIPTIN PHOSPHATE MONOHYDRATE".to_string(), "metformin HCl 500 mg".to_string()]).as_deref(),
+            Some("ingredients:METFORMIN+SITAGLIPTIN")
+        );
+        assert_eq!(equivalence_key(None, &[]), None);
+    }
+
+    #[test]
+    fn test_group_equivalents_across_sources() {
+        let results = vec![
+            result("openfda", "0093-1048", None, &["METFORMIN HYDROCHLORIDE"]),
+            result("ema", "EU/1/00/001", Some("A10BA02"), &["metformin hydrochloride"]),
+            result("mhra", "PL 1/2", Some("A10BA02"), &["Metformin"]),
+            result("openfda", "0000-0001", None, &["IBUPROFEN"]),
+        ];
+
+        let groups = group_equivalents(&results);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].equivalence_key, "atc:A10BA02");
+        assert_eq!(groups[0].sources, vec!["openfda", "ema", "mhra"]);
+        assert_eq!(groups[0].source_ids.len(), 3);
+    }
+
+    #[test]
+    fn test_source_list() {
+        let request = |sources: Option<&str>| FederatedSearchRequest {
+            query: None,
+            ingredient: None,
+            atc_code: None,
+            sources: sources.map(str::to_string),
+            limit: None,
+            offset: None,
+        };
+
+        assert_eq!(request(None).source_list().unwrap().len(), FEDERATED_SOURCES.len());
+        assert_eq!(request(Some("EMA, openfda,ema")).source_list().unwrap(), vec!["openfda", "ema"]);
+        assert_eq!(request(Some("health-canada")).source_list().unwrap(), vec!["health_canada"]);
+        assert!(request(Some("pmda")).source_list().is_err());
+    }
+}
@@ -0,0 +1,472 @@
+/// FHIR facade models: code systems, search parameters, SMART token exchange, registered
+/// FHIR clients, and the FHIR resources built from catalog and inventory records.
+///
+/// The facade is read-only, so resources are built as JSON rather than typed structs:
+/// - Medication (R4): a product of our catalog, coded by NDC and ATC
+/// - MedicationKnowledge (R4): an OpenFDA catalog entry, coded by NDC, RxNorm and ATC
+/// - InventoryItem (R5, the only version that has it): a marketplace listing
+
+use chrono::{DateTime, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sqlx::FromRow;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+use crate::models::{inventory::InventoryWithDetails, openfda::OpenFdaCatalogEntry, pharmaceutical::{PharmaceuticalResponse, ProductDomain}};
+
+pub const FHIR_VERSION: &str = "4.0.1";
+pub const FHIR_JSON: &str = "application/fhir+json";
+
+pub const NDC_SYSTEM: &str = "http://hl7.org/fhir/sid/ndc";
+pub const ATC_SYSTEM: &str = "http://www.whocc.no/atc";
+pub const RXNORM_SYSTEM: &str = "http://www.nlm.nih.gov/research/umls/rxnorm";
+const INVENTORY_NAME_TYPE_SYSTEM: &str = "http://hl7.org/fhir/inventoryitem-nametype";
+
+/// Resource types the facade serves
+pub const FHIR_RESOURCE_TYPES: [&str; 3] = ["Medication", "MedicationKnowledge", "InventoryItem"];
+
+/// Lifetime of tokens issued to FHIR clients (SMART backend services: at most five minutes)
+pub const FHIR_TOKEN_TTL_SECS: usize = 300;
+
+/// Clients a user may register
+pub const MAX_CLIENTS_PER_USER: i64 = 10;
+
+const DEFAULT_PAGE_SIZE: i64 = 20;
+const MAX_PAGE_SIZE: i64 = 100;
+
+// ============================================================================
+// SEARCH
+// ============================================================================
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FhirSearchParams {
+    /// Token `[system|]code`, e.g. `http://hl7.org/fhir/sid/ndc|0002-1433`; a bare code is an NDC
+    pub code: Option<String>,
+    /// Page size (default 20, at most 100)
+    #[serde(rename = "_count")]
+    pub count: Option<i64>,
+    /// Entries to skip; the `next` link of a page carries it
+    #[serde(rename = "_offset")]
+    pub offset: Option<i64>,
+}
+
+/// The `code` search parameter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodeToken {
+    Ndc(String),
+    Atc(String),
+}
+
+impl FhirSearchParams {
+    /// (page size, offset)
+    pub fn page(&self) -> (i64, i64) {
+        (
+            self.count.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE),
+            self.offset.unwrap_or(0).max(0),
+        )
+    }
+
+    /// The `code` token; Err with the system when it isn't one the facade searches by
+    pub fn code_token(&self) -> Result<Option<CodeToken>, String> {
+        let Some(token) = self.code.as_deref().map(str::trim).filter(|token| !token.is_empty()) else {
+            return Ok(None);
+        };
+        match token.split_once('|') {
+            None => Ok(Some(CodeToken::Ndc(token.to_string()))),
+            Some((NDC_SYSTEM, code)) => Ok(Some(CodeToken::Ndc(code.to_string()))),
+            Some((ATC_SYSTEM, code)) => Ok(Some(CodeToken::Atc(code.to_uppercase()))),
+            Some((system, _)) => Err(system.to_string()),
+        }
+    }
+}
+
+// ============================================================================
+// SMART TOKEN EXCHANGE
+// ============================================================================
+
+/// Form body of POST /fhir/auth/token; credentials may come in the body or as HTTP Basic
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SmartTokenRequest {
+    /// Only `client_credentials`
+    pub grant_type: String,
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+    /// Space-separated, e.g. `system/Medication.read system/InventoryItem.read`
+    pub scope: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SmartTokenResponse {
+    pub access_token: String,
+    /// Always `bearer`
+    pub token_type: String,
+    pub expires_in: usize,
+    pub scope: String,
+}
+
+/// OAuth error body of the token endpoint (RFC 6749 section 5.2)
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SmartTokenError {
+    pub error: String,
+    pub error_description: String,
+}
+
+/// Every scope a client can be granted: read access to each resource type
+pub fn supported_scopes() -> Vec<String> {
+    FHIR_RESOURCE_TYPES.iter().map(|resource| format!("system/{}.read", resource)).collect()
+}
+
+/// Scopes granted for a request: the supported ones it asks for (`system/*.read` asks for
+/// all), or all when it names none. None when it only asks for scopes we don't have.
+pub fn granted_scopes(requested: Option<&str>) -> Option<String> {
+    let supported = supported_scopes();
+    let requested: Vec<&str> = requested.map(|scope| scope.split_whitespace().collect()).unwrap_or_default();
+    if requested.is_empty() || requested.contains(&"system/*.read") {
+        return Some(supported.join(" "));
+    }
+
+    let granted: Vec<&str> = requested.into_iter().filter(|scope| supported.iter().any(|s| s == scope)).collect();
+    (!granted.is_empty()).then(|| granted.join(" "))
+}
+
+// ============================================================================
+// CLIENTS
+// ============================================================================
+
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct FhirClient {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub client_id: String,
+    #[serde(skip_serializing)]
+    pub secret_hash: String,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateFhirClientRequest {
+    /// e.g. the hospital system using it; at most 255 characters
+    pub name: String,
+}
+
+/// Returned on registration only - the secret is never shown again
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FhirClientWithSecret {
+    #[serde(flatten)]
+    pub client: FhirClient,
+    pub client_secret: String,
+}
+
+// ============================================================================
+// RESOURCES
+// ============================================================================
+
+/// Whether a catalog product is a medication (devices aren't)
+pub fn is_medication(pharma: &PharmaceuticalResponse) -> bool {
+    pharma.product_domain != ProductDomain::MedicalDevice.as_str()
+}
+
+fn coding(system: &str, code: &str) -> Value {
+    json!({ "system": system, "code": code })
+}
+
+/// Medication for a catalog product
+pub fn medication(pharma: &PharmaceuticalResponse, atc_codes: &[String]) -> Value {
+    let mut codings = Vec::new();
+    if let Some(ndc) = pharma.ndc_code.as_deref() {
+        codings.push(json!({ "system": NDC_SYSTEM, "code": ndc, "display": pharma.brand_name }));
+    }
+    codings.extend(atc_codes.iter().map(|atc| coding(ATC_SYSTEM, atc)));
+
+    let text = match pharma.strength.as_deref() {
+        Some(strength) => format!("{} {}", pharma.brand_name, strength),
+        None => pharma.brand_name.clone(),
+    };
+
+    let mut resource = json!({
+        "resourceType": "Medication",
+        "id": pharma.id,
+        "code": { "coding": codings, "text": text },
+        "status": "active",
+        "manufacturer": { "display": pharma.manufacturer },
+        "ingredient": [{ "itemCodeableConcept": { "text": pharma.generic_name }, "isActive": true }],
+    });
+    if let Some(form) = pharma.dosage_form.as_deref() {
+        resource["form"] = json!({ "text": form });
+    }
+    resource
+}
+
+/// MedicationKnowledge for an OpenFDA catalog entry; its id is the product NDC
+pub fn medication_knowledge(entry: &OpenFdaCatalogEntry, atc_codes: &[String], today: chrono::NaiveDate) -> Value {
+    let mut codings = vec![json!({ "system": NDC_SYSTEM, "code": entry.product_ndc, "display": entry.brand_name })];
+    let rxcuis = entry
+        .openfda_data
+        .as_ref()
+        .and_then(|data| data.get("rxcui"))
+        .and_then(Value::as_array)
+        .map(|rxcuis| rxcuis.iter().filter_map(Value::as_str).collect::<Vec<_>>())
+        .unwrap_or_default();
+    codings.extend(rxcuis.into_iter().map(|rxcui| coding(RXNORM_SYSTEM, rxcui)));
+    codings.extend(atc_codes.iter().map(|atc| coding(ATC_SYSTEM, atc)));
+
+    let expired = entry.listing_expiration_date.is_some_and(|date| date < today);
+    let mut resource = json!({
+        "resourceType": "MedicationKnowledge",
+        "id": entry.product_ndc,
+        "meta": { "lastUpdated": entry.updated_at },
+        "code": { "coding": codings, "text": entry.brand_name },
+        "status": if expired { "inactive" } else { "active" },
+        "manufacturer": { "display": entry.labeler_name },
+        "synonym": [entry.generic_name],
+    });
+
+    if let Some(form) = entry.dosage_form.as_deref() {
+        resource["doseForm"] = json!({ "text": form });
+    }
+    if let Some(routes) = entry.route.as_ref().filter(|routes| !routes.is_empty()) {
+        resource["intendedRoute"] = routes.iter().map(|route| json!({ "text": route })).collect();
+    }
+    if let Some(product_type) = entry.product_type.as_deref() {
+        resource["productType"] = json!([{ "text": product_type }]);
+    }
+
+    let ingredients: Vec<Value> = entry
+        .active_ingredients
+        .as_ref()
+        .and_then(Value::as_array)
+        .map(|ingredients| {
+            ingredients
+                .iter()
+                .filter_map(|ingredient| ingredient.get("name").and_then(Value::as_str))
+                .map(|name| json!({ "itemCodeableConcept": { "text": name }, "isActive": true }))
+                .collect()
+        })
+        .unwrap_or_default();
+    if !ingredients.is_empty() {
+        resource["ingredient"] = Value::Array(ingredients);
+    }
+
+    let mut classifications = Vec::new();
+    if !atc_codes.is_empty() {
+        classifications.push(json!({
+            "type": { "text": "ATC" },
+            "classification": atc_codes.iter().map(|atc| json!({ "coding": [coding(ATC_SYSTEM, atc)] })).collect::<Vec<_>>(),
+        }));
+    }
+    if let Some(classes) = entry.pharm_class.as_ref().filter(|classes| !classes.is_empty()) {
+        classifications.push(json!({
+            "type": { "text": "Pharmacologic class" },
+            "classification": classes.iter().map(|class| json!({ "text": class })).collect::<Vec<_>>(),
+        }));
+    }
+    if !classifications.is_empty() {
+        resource["medicineClassification"] = Value::Array(classifications);
+    }
+
+    if let Some(schedule) = entry.dea_schedule.as_deref() {
+        resource["regulatory"] = json!([{
+            "regulatoryAuthority": { "display": "FDA" },
+            "schedule": [{ "schedule": { "text": schedule } }],
+        }]);
+    }
+    resource
+}
+
+/// InventoryItem for a marketplace listing. The storage location stays with the seller.
+pub fn inventory_item(listing: &InventoryWithDetails) -> Value {
+    let inventory = &listing.inventory;
+    let pharma = &listing.pharmaceutical;
+
+    let mut codings = Vec::new();
+    if let Some(ndc) = pharma.ndc_code.as_deref() {
+        codings.push(json!({ "system": NDC_SYSTEM, "code": ndc, "display": pharma.brand_name }));
+    }
+
+    let mut resource = json!({
+        "resourceType": "InventoryItem",
+        "id": inventory.id,
+        "meta": { "lastUpdated": inventory.updated_at },
+        "status": "active",
+        "code": [{ "coding": codings, "text": pharma.brand_name }],
+        "name": [{
+            "nameType": { "system": INVENTORY_NAME_TYPE_SYSTEM, "code": "trade-name" },
+            "language": "en",
+            "name": pharma.brand_name,
+        }],
+        "responsibleOrganization": [{
+            "role": { "text": "seller" },
+            "organization": { "display": listing.user.company_name },
+        }],
+        "inventoryStatus": [{ "text": inventory.status }],
+        "netContent": { "value": inventory.quantity },
+        "instance": { "lotNumber": inventory.batch_number, "expiry": inventory.expiry_date },
+        "productReference": { "reference": format!("Medication/{}", pharma.id) },
+    });
+    if let Some(price) = inventory.unit_price.and_then(|price| price.to_f64()) {
+        resource["characteristic"] = json!([{
+            "characteristicType": { "text": "unit-price" },
+            "valueMoney": { "value": price, "currency": "USD" },
+        }]);
+    }
+    resource
+}
+
+/// Searchset Bundle of a page of resources
+pub fn search_bundle(base_url: &str, resources: Vec<Value>, self_url: String, next_url: Option<String>) -> Value {
+    let mut links = vec![json!({ "relation": "self", "url": self_url })];
+    if let Some(next_url) = next_url {
+        links.push(json!({ "relation": "next", "url": next_url }));
+    }
+
+    let entries: Vec<Value> = resources
+        .into_iter()
+        .map(|resource| {
+            let full_url = format!(
+                "{}/{}/{}",
+                base_url,
+                resource["resourceType"].as_str().unwrap_or_default(),
+                resource["id"].as_str().unwrap_or_default()
+            );
+            json!({ "fullUrl": full_url, "resource": resource, "search": { "mode": "match" } })
+        })
+        .collect();
+
+    json!({
+        "resourceType": "Bundle",
+        "type": "searchset",
+        "link": links,
+        "entry": entries,
+    })
+}
+
+/// OperationOutcome of an error with the given HTTP status
+pub fn operation_outcome(status: u16, diagnostics: &str) -> Value {
+    let code = match status {
+        401 => "login",
+        403 => "forbidden",
+        404 => "not-found",
+        409 => "conflict",
+        429 => "throttled",
+        400..=499 => "invalid",
+        _ => "exception",
+    };
+    json!({
+        "resourceType": "OperationOutcome",
+        "issue": [{ "severity": "error", "code": code, "diagnostics": diagnostics }],
+    })
+}
+
+/// CapabilityStatement served at GET /fhir/metadata
+pub fn capability_statement(base_url: &str, software_version: &str, published: DateTime<Utc>) -> Value {
+    let search_params = json!([
+        {
+            "name": "code",
+            "type": "token",
+            "documentation": "NDC (http://hl7.org/fhir/sid/ndc, the default system) or ATC (http://www.whocc.no/atc, MedicationKnowledge only, matches by prefix)",
+        },
+        { "name": "_count", "type": "number", "documentation": "Page size, at most 100" },
+    ]);
+    let resources: Vec<Value> = FHIR_RESOURCE_TYPES
+        .iter()
+        .map(|resource_type| {
+            json!({
+                "type": resource_type,
+                "interaction": [{ "code": "read" }, { "code": "search-type" }],
+                "searchParam": search_params,
+            })
+        })
+        .collect();
+
+    json!({
+        "resourceType": "CapabilityStatement",
+        "status": "active",
+        "date": published,
+        "kind": "instance",
+        "software": { "name": "Atlas Pharma", "version": software_version },
+        "implementation": { "description": "Atlas Pharma FHIR facade", "url": base_url },
+        "fhirVersion": FHIR_VERSION,
+        "format": [FHIR_JSON, "json"],
+        "rest": [{
+            "mode": "server",
+            "security": {
+                "cors": true,
+                "service": [{
+                    "coding": [{
+                        "system": "http://terminology.hl7.org/CodeSystem/restful-security-service",
+                        "code": "SMART-on-FHIR",
+                    }],
+                }],
+                "extension": [{
+                    "url": "http://fhir-registry.smarthealthit.org/StructureDefinition/oauth-uris",
+                    "extension": [{ "url": "token", "valueUri": format!("{}/auth/token", base_url) }],
+                }],
+                "description": "Bearer JWT from POST /fhir/auth/token (client_credentials with a client registered at /api/fhir/clients) or a regular sign-in",
+            },
+            "resource": resources,
+        }],
+    })
+}
+
+/// SMART configuration served at GET /fhir/.well-known/smart-configuration
+pub fn smart_configuration(base_url: &str) -> Value {
+    json!({
+        "token_endpoint": format!("{}/auth/token", base_url),
+        "grant_types_supported": ["client_credentials"],
+        "token_endpoint_auth_methods_supported": ["client_secret_basic", "client_secret_post"],
+        "scopes_supported": supported_scopes(),
+        "capabilities": ["client-confidential-symmetric"],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(code: &str) -> FhirSearchParams {
+        FhirSearchParams { code: Some(code.to_string()), ..Default::default() }
+    }
+
+    #[test]
+    fn test_code_token() {
+        assert_eq!(params("0002-1433").code_token(), Ok(Some(CodeToken::Ndc("0002-1433".to_string()))));
+        assert_eq!(
+            params("http://hl7.org/fhir/sid/ndc|0002-1433-80").code_token(),
+            Ok(Some(CodeToken::Ndc("0002-1433-80".to_string())))
+        );
+        assert_eq!(params("http://www.whocc.no/atc|a10bj").code_token(), Ok(Some(CodeToken::Atc("A10BJ".to_string()))));
+        assert_eq!(params("http://snomed.info/sct|123").code_token(), Err("http://snomed.info/sct".to_string()));
+        assert_eq!(params(" ").code_token(), Ok(None));
+    }
+
+    #[test]
+    fn test_page() {
+        assert_eq!(FhirSearchParams::default().page(), (20, 0));
+        let params = FhirSearchParams { count: Some(500), offset: Some(-3), ..Default::default() };
+        assert_eq!(params.page(), (100, 0));
+    }
+
+    #[test]
+    fn test_granted_scopes() {
+        let all = supported_scopes().join(" ");
+        assert_eq!(granted_scopes(None), Some(all.clone()));
+        assert_eq!(granted_scopes(Some("system/*.read")), Some(all));
+        assert_eq!(
+            granted_scopes(Some("system/Medication.read system/Patient.read")),
+            Some("system/Medication.read".to_string())
+        );
+        assert_eq!(granted_scopes(Some("system/Patient.read")), None);
+    }
+
+    #[test]
+    fn test_operation_outcome_codes() {
+        assert_eq!(operation_outcome(404, "Resource not found")["issue"][0]["code"], "not-found");
+        assert_eq!(operation_outcome(422, "Invalid")["issue"][0]["code"], "invalid");
+        assert_eq!(operation_outcome(500, "Internal server error")["issue"][0]["code"], "exception");
+    }
+}
@@ -0,0 +1,119 @@
+/// Health check models: per-dependency results and the overall readiness of the API
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, PartialOrd, Ord, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    Ok,
+    /// Serving, with a non-essential dependency failing
+    Degraded,
+    /// An essential dependency is failing; the instance can't serve requests
+    Down,
+}
+
+/// Result of checking one dependency
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct DependencyCheck {
+    #[schema(value_type = String)]
+    pub name: &'static str,
+    pub status: HealthStatus,
+    /// Essential dependencies take the instance down when they fail
+    pub critical: bool,
+    pub latency_ms: Option<u64>,
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+}
+
+impl DependencyCheck {
+    pub fn ok(name: &'static str, critical: bool, latency_ms: u64) -> Self {
+        Self { name, status: HealthStatus::Ok, critical, latency_ms: Some(latency_ms), message: None, details: None }
+    }
+
+    /// A failing check: down for essential dependencies, degraded for the rest
+    pub fn failed(name: &'static str, critical: bool, latency_ms: Option<u64>, message: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: if critical { HealthStatus::Down } else { HealthStatus::Degraded },
+            critical,
+            latency_ms,
+            message: Some(message.into()),
+            details: None,
+        }
+    }
+
+    pub fn with_status(mut self, status: HealthStatus, message: impl Into<String>) -> Self {
+        self.status = status;
+        self.message = Some(message.into());
+        self
+    }
+
+    pub fn with_details(mut self, details: serde_json::Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct HealthReport {
+    pub status: HealthStatus,
+    #[schema(value_type = String)]
+    pub service: &'static str,
+    #[schema(value_type = String)]
+    pub version: &'static str,
+    pub checks: Vec<DependencyCheck>,
+    pub checked_at: DateTime<Utc>,
+}
+
+impl HealthReport {
+    pub fn new(checks: Vec<DependencyCheck>) -> Self {
+        Self {
+            status: overall_status(&checks),
+            service: "atlas_pharma",
+            version: env!("CARGO_PKG_VERSION"),
+            checks,
+            checked_at: Utc::now(),
+        }
+    }
+}
+
+/// Down when an essential dependency is down, degraded when anything else fails
+pub fn overall_status(checks: &[DependencyCheck]) -> HealthStatus {
+    checks
+        .iter()
+        .map(|check| match check.status {
+            HealthStatus::Down if !check.critical => HealthStatus::Degraded,
+            status => status,
+        })
+        .max()
+        .unwrap_or(HealthStatus::Ok)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overall_status() {
+        let database = DependencyCheck::ok("database", true, 2);
+        let claude = DependencyCheck::ok("claude_api", false, 120);
+        assert_eq!(overall_status(&[database.clone(), claude.clone()]), HealthStatus::Ok);
+
+        let claude_down = DependencyCheck::failed("claude_api", false, None, "unreachable");
+        assert_eq!(claude_down.status, HealthStatus::Degraded);
+        assert_eq!(overall_status(&[database.clone(), claude_down.clone()]), HealthStatus::Degraded);
+
+        let database_down = DependencyCheck::failed("database", true, None, "timed out");
+        assert_eq!(overall_status(&[database_down, claude_down]), HealthStatus::Down);
+    }
+
+    #[test]
+    fn test_non_critical_down_only_degrades() {
+        let storage = DependencyCheck::ok("file_storage", false, 1).with_status(HealthStatus::Down, "full");
+        assert_eq!(overall_status(&[storage]), HealthStatus::Degraded);
+        assert_eq!(overall_status(&[]), HealthStatus::Ok);
+    }
+}
@@ -0,0 +1,299 @@
+/// Structured ingredient models
+///
+/// Active and inactive ingredients are stored one row per ingredient, tagged with the
+/// allergen classes they contain, so buyers with formulary restrictions can search for
+/// e.g. lactose-free or gelatin-free products.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+// ============================================================================
+// ENUMS
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum IngredientRole {
+    Active,
+    Inactive,
+}
+
+impl IngredientRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IngredientRole::Active => "active",
+            IngredientRole::Inactive => "inactive",
+        }
+    }
+}
+
+/// Allergen / formulary-restriction classes that ingredients are tagged with
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Allergen {
+    Lactose,
+    Gelatin,
+    Gluten,
+    Peanut,
+    Soy,
+    Egg,
+    Sulfite,
+    AzoDye,
+}
+
+impl Allergen {
+    pub const ALL: [Allergen; 8] = [
+        Allergen::Lactose,
+        Allergen::Gelatin,
+        Allergen::Gluten,
+        Allergen::Peanut,
+        Allergen::Soy,
+        Allergen::Egg,
+        Allergen::Sulfite,
+        Allergen::AzoDye,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Allergen::Lactose => "lactose",
+            Allergen::Gelatin => "gelatin",
+            Allergen::Gluten => "gluten",
+            Allergen::Peanut => "peanut",
+            Allergen::Soy => "soy",
+            Allergen::Egg => "egg",
+            Allergen::Sulfite => "sulfite",
+            Allergen::AzoDye => "azo_dye",
+        }
+    }
+
+    /// Ingredient name fragments (lowercase, matched on word boundaries) that indicate this allergen
+    fn keywords(&self) -> &'static [&'static str] {
+        match self {
+            Allergen::Lactose => &["lactose", "milk", "whey", "casein", "caseinate"],
+            Allergen::Gelatin => &["gelatin", "gelatine"],
+            Allergen::Gluten => &["gluten", "wheat", "barley", "rye"],
+            Allergen::Peanut => &["peanut", "arachis"],
+            Allergen::Soy => &["soy", "soya", "soybean"],
+            Allergen::Egg => &["egg", "ovalbumin", "lysozyme"],
+            Allergen::Sulfite => &[
+                "sulfite", "sulphite", "bisulfite", "bisulphite", "metabisulfite",
+                "metabisulphite", "sulfur dioxide", "sulphur dioxide",
+            ],
+            Allergen::AzoDye => &[
+                "tartrazine", "sunset yellow", "allura red", "ponceau", "azorubine",
+                "carmoisine", "amaranth", "fd&c yellow no. 5", "fd&c yellow no. 6",
+                "fd&c red no. 40", "e102", "e110", "e122", "e124", "e129",
+            ],
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        let normalized = name.trim().to_lowercase().replace(['-', ' '], "_");
+        Self::ALL.into_iter().find(|a| a.as_str() == normalized)
+    }
+
+    /// Allergen classes present in an ingredient name
+    pub fn detect(ingredient_name: &str) -> Vec<Allergen> {
+        let name = ingredient_name.to_lowercase();
+        Self::ALL
+            .into_iter()
+            .filter(|allergen| allergen.keywords().iter().any(|k| contains_word(&name, k)))
+            .collect()
+    }
+
+    /// Parse a `free_of` search filter such as `lactose-free,gelatin` into allergen classes
+    pub fn parse_filter(filter: &str) -> Result<Vec<Allergen>, String> {
+        let mut allergens = Vec::new();
+
+        for part in filter.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let lower = part.to_lowercase();
+            let name = lower
+                .strip_suffix("-free")
+                .or_else(|| lower.strip_suffix(" free"))
+                .or_else(|| lower.strip_suffix("_free"))
+                .unwrap_or(&lower);
+
+            let allergen = Allergen::from_name(name).ok_or_else(|| {
+                format!(
+                    "Unknown allergen '{}'. Supported: {}",
+                    part,
+                    Self::ALL.iter().map(|a| a.as_str()).collect::<Vec<_>>().join(", ")
+                )
+            })?;
+
+            if !allergens.contains(&allergen) {
+                allergens.push(allergen);
+            }
+        }
+
+        Ok(allergens)
+    }
+}
+
+impl std::fmt::Display for Allergen {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+// ============================================================================
+// PARSING
+// ============================================================================
+
+/// An ingredient parsed from label text or supplied by the user
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ParsedIngredient {
+    pub name: String,
+    pub strength: Option<String>,
+    pub role: IngredientRole,
+    pub allergens: Vec<Allergen>,
+}
+
+impl ParsedIngredient {
+    pub fn new(name: &str, strength: Option<String>, role: IngredientRole) -> Self {
+        let name = name.trim().to_string();
+        let allergens = Allergen::detect(&name);
+        Self {
+            name,
+            strength: strength.map(|s| s.trim().to_string()).filter(|s| !s.is_empty()),
+            role,
+            allergens,
+        }
+    }
+}
+
+/// Split a label ingredient list ("Inactive ingredients: lactose monohydrate, magnesium stearate (E470b); ...")
+/// into individual ingredients. Separators inside parentheses are ignored.
+pub fn parse_ingredient_list(text: &str, role: IngredientRole) -> Vec<ParsedIngredient> {
+    let body = match text.find(':') {
+        Some(idx) if text[..idx].to_lowercase().contains("ingredient") || text[..idx].to_lowercase().contains("excipient") => &text[idx + 1..],
+        _ => text,
+    };
+
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0usize;
+
+    for c in body.chars() {
+        match c {
+            '(' | '[' => {
+                depth += 1;
+                current.push(c);
+            }
+            ')' | ']' => {
+                depth = depth.saturating_sub(1);
+                current.push(c);
+            }
+            ',' | ';' | '\n' if depth == 0 => items.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    items.push(current);
+
+    items
+        .iter()
+        .map(|item| item.trim().trim_end_matches('.').trim())
+        .map(|item| item.strip_prefix("and ").unwrap_or(item).trim())
+        .filter(|item| !item.is_empty())
+        .map(|item| ParsedIngredient::new(item, None, role))
+        .collect()
+}
+
+/// Word-boundary substring match so that e.g. "rye" does not match "glyceryl"
+fn contains_word(haystack: &str, needle: &str) -> bool {
+    haystack.match_indices(needle).any(|(idx, _)| {
+        let before = haystack[..idx].chars().next_back();
+        let after = haystack[idx + needle.len()..].chars().next();
+        !before.map(|c| c.is_alphanumeric()).unwrap_or(false)
+            && !after.map(|c| c.is_alphanumeric()).unwrap_or(false)
+    })
+}
+
+// ============================================================================
+// DATABASE MODELS
+// ============================================================================
+
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct PharmaceuticalIngredient {
+    pub id: Uuid,
+    pub pharmaceutical_id: Uuid,
+    pub role: String,
+    pub name: String,
+    pub strength: Option<String>,
+    pub allergens: Vec<String>,
+    pub position: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+// ============================================================================
+// API MODELS
+// ============================================================================
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct IngredientInput {
+    pub name: String,
+    pub strength: Option<String>,
+}
+
+/// Replace the ingredient list of a pharmaceutical. Inactive ingredients may be given
+/// as a list or as label text (`inactive_ingredients_text`), which is parsed.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SetIngredientsRequest {
+    #[serde(default)]
+    pub active_ingredients: Vec<IngredientInput>,
+    pub inactive_ingredients: Option<Vec<IngredientInput>>,
+    pub inactive_ingredients_text: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PharmaceuticalIngredientsResponse {
+    pub pharmaceutical_id: Uuid,
+    pub active_ingredients: Vec<PharmaceuticalIngredient>,
+    pub inactive_ingredients: Vec<PharmaceuticalIngredient>,
+    /// False until the excipient list has been recorded; `free_of` filters never match undeclared products
+    pub excipients_declared: bool,
+    pub allergens: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_allergens() {
+        assert_eq!(Allergen::detect("Lactose monohydrate"), vec![Allergen::Lactose]);
+        assert_eq!(Allergen::detect("Gelatine (bovine)"), vec![Allergen::Gelatin]);
+        assert_eq!(Allergen::detect("Sodium metabisulfite"), vec![Allergen::Sulfite]);
+        assert_eq!(Allergen::detect("FD&C Yellow No. 5 (tartrazine)"), vec![Allergen::AzoDye]);
+        assert!(Allergen::detect("Magnesium stearate").is_empty());
+        // Word boundaries: "rye" must not match inside other words
+        assert!(Allergen::detect("Glyceryl behenate").is_empty());
+    }
+
+    #[test]
+    fn test_parse_ingredient_list() {
+        let parsed = parse_ingredient_list(
+            "Inactive ingredients: lactose monohydrate, magnesium stearate (E470b; vegetable), and gelatin.",
+            IngredientRole::Inactive,
+        );
+        let names: Vec<&str> = parsed.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["lactose monohydrate", "magnesium stearate (E470b; vegetable)", "gelatin"]);
+        assert_eq!(parsed[0].allergens, vec![Allergen::Lactose]);
+        assert_eq!(parsed[2].allergens, vec![Allergen::Gelatin]);
+        assert!(parsed.iter().all(|p| p.role == IngredientRole::Inactive));
+    }
+
+    #[test]
+    fn test_parse_filter() {
+        assert_eq!(
+            Allergen::parse_filter("lactose-free, Gelatin free,azo-dye").unwrap(),
+            vec![Allergen::Lactose, Allergen::Gelatin, Allergen::AzoDye]
+        );
+        assert_eq!(Allergen::parse_filter("lactose,lactose-free").unwrap(), vec![Allergen::Lactose]);
+        assert!(Allergen::parse_filter("unicorn-free").is_err());
+        assert!(Allergen::parse_filter("").unwrap().is_empty());
+    }
+}
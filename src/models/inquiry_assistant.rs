@@ -1,6 +1,7 @@
 /// Inquiry Assistant models for AI-powered inquiry response suggestions
 
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use sqlx::FromRow;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
@@ -24,19 +25,24 @@ pub struct InquiryAiSuggestion {
     pub was_edited: bool,
     pub final_message_id: Option<Uuid>,
     pub created_at: DateTime<Utc>,
+    pub language: String,
+    pub language_source: String,
+    pub english_translation: Option<String>,
 }
 
 // ============================================================================
 // API Request/Response Models
 // ============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct GenerateSuggestionRequest {
     pub suggestion_type: SuggestionType,
     pub custom_instructions: Option<String>, // User can guide AI: "be more formal", "offer 10% discount", etc.
+    pub language: Option<String>, // ISO 639-1; overrides the detected counterparty language
+    pub use_memory: Option<bool>, // Replay this inquiry's earlier suggestions (default true)
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum SuggestionType {
     InitialResponse,
@@ -62,13 +68,16 @@ impl ToString for SuggestionType {
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct SuggestionResponse {
     pub id: Uuid,
     pub inquiry_id: Uuid,
     pub suggestion_type: String,
     pub suggestion_text: String,
     pub reasoning: Option<String>,
+    pub language: String,
+    pub language_source: String,
+    pub english_translation: Option<String>,
     pub context_used: InquiryContext,
     pub ai_cost_usd: String,
     pub created_at: DateTime<Utc>,
@@ -86,6 +95,9 @@ impl From<InquiryAiSuggestion> for SuggestionResponse {
             suggestion_type: sug.suggestion_type,
             suggestion_text: sug.suggestion_text,
             reasoning: sug.ai_reasoning,
+            language: sug.language,
+            language_source: sug.language_source,
+            english_translation: sug.english_translation,
             context_used: context,
             ai_cost_usd: sug.ai_cost_usd.to_string(),
             created_at: sug.created_at,
@@ -93,7 +105,7 @@ impl From<InquiryAiSuggestion> for SuggestionResponse {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Default)]
+#[derive(Debug, Serialize, Deserialize, Default, ToSchema)]
 pub struct InquiryContext {
     pub product_name: Option<String>,
     pub quantity_requested: i32,
@@ -107,17 +119,49 @@ pub struct InquiryContext {
     pub inquiry_status: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct AcceptSuggestionRequest {
     pub edited_text: Option<String>, // If user edited the suggestion before sending
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct AcceptSuggestionResponse {
     pub message_id: Uuid,
     pub was_edited: bool,
 }
 
+#[derive(Debug, Deserialize, Default, ToSchema)]
+pub struct RejectSuggestionRequest {
+    pub reason: Option<String>, // Fed back to the assistant: "price too low", "too pushy", etc.
+}
+
+/// Languages for inquiry suggestions (defaults apply until the user saves preferences)
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct LanguagePreferences {
+    /// Language the user writes in, used when they are the counterparty
+    pub preferred_language: Option<String>,
+    /// Suggestion language when the counterparty's language is unknown
+    pub negotiation_language: String,
+    pub include_translation: bool,
+}
+
+impl Default for LanguagePreferences {
+    fn default() -> Self {
+        Self {
+            preferred_language: None,
+            negotiation_language: "en".to_string(),
+            include_translation: true,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateLanguagePreferencesRequest {
+    pub preferred_language: Option<String>,
+    pub negotiation_language: Option<String>,
+    pub include_translation: Option<bool>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct SuggestionHistoryResponse {
     pub suggestions: Vec<SuggestionHistoryItem>,
@@ -135,6 +179,114 @@ pub struct SuggestionHistoryItem {
     pub created_at: DateTime<Utc>,
 }
 
+// ============================================================================
+// Conversation Memory
+// ============================================================================
+
+/// Estimated tokens of earlier turns replayed verbatim with each new suggestion request
+pub const MEMORY_TOKEN_BUDGET: i32 = 3000;
+
+/// Older turns are condensed to one line each; beyond this many only the newest are kept
+pub const MAX_CONDENSED_TURNS: usize = 20;
+
+const CONDENSED_RESPONSE_CHARS: usize = 200;
+
+/// One suggestion request of the seller's assistant thread and what became of it
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct AssistantTurn {
+    pub id: Uuid,
+    pub inquiry_id: Uuid,
+    pub user_id: Uuid,
+    pub suggestion_id: Uuid,
+    pub suggestion_type: String,
+    pub request_text: String,
+    pub response_text: String,
+    pub outcome: String,
+    pub sent_text: Option<String>,
+    pub rejection_reason: Option<String>,
+    pub estimated_tokens: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AssistantThreadResponse {
+    pub inquiry_id: Uuid,
+    pub turns: Vec<AssistantTurn>,
+    /// Turns the next suggestion request would replay verbatim
+    pub replayed_turns: usize,
+    /// Older turns it would include only as one-line summaries
+    pub condensed_turns: usize,
+    pub replayed_tokens: i32,
+    pub token_budget: i32,
+}
+
+/// Rough token count (about four characters per token)
+pub fn estimate_tokens(text: &str) -> i32 {
+    ((text.chars().count() + 3) / 4) as i32
+}
+
+/// Index splitting a thread into condensed turns (`..split`) and replayed turns
+/// (`split..`): the newest turns whose combined size fits the budget are replayed
+pub fn memory_split(turns: &[AssistantTurn], token_budget: i32) -> usize {
+    let mut used = 0;
+    let mut split = turns.len();
+
+    for (index, turn) in turns.iter().enumerate().rev() {
+        if used + turn.estimated_tokens > token_budget {
+            break;
+        }
+        used += turn.estimated_tokens;
+        split = index;
+    }
+
+    split
+}
+
+/// The seller's side of a turn, as replayed to the model
+pub fn describe_request(suggestion_type: &str, custom_instructions: Option<&str>) -> String {
+    match custom_instructions.map(str::trim).filter(|i| !i.is_empty()) {
+        Some(instructions) => format!(
+            "Suggest a {} response. Seller instructions: {}",
+            suggestion_type.replace('_', " "),
+            instructions
+        ),
+        None => format!("Suggest a {} response.", suggestion_type.replace('_', " ")),
+    }
+}
+
+/// What the seller did with a suggestion, in the words given to the model
+pub fn outcome_note(turn: &AssistantTurn) -> String {
+    match turn.outcome.as_str() {
+        "accepted" => "The seller sent this suggestion unchanged.".to_string(),
+        "edited" => format!(
+            "The seller edited this suggestion before sending it. Sent text: {}",
+            turn.sent_text.as_deref().unwrap_or("(not recorded)")
+        ),
+        "rejected" => match turn.rejection_reason.as_deref().map(str::trim).filter(|r| !r.is_empty()) {
+            Some(reason) => format!("The seller rejected this suggestion. Reason: {}", reason),
+            None => "The seller rejected this suggestion.".to_string(),
+        },
+        _ => "The seller has not used this suggestion.".to_string(),
+    }
+}
+
+/// One-line summary of a turn that no longer fits the replay budget
+pub fn condense_turn(turn: &AssistantTurn) -> String {
+    let mut response: String = turn.response_text.chars().take(CONDENSED_RESPONSE_CHARS).collect();
+    if turn.response_text.chars().count() > CONDENSED_RESPONSE_CHARS {
+        response.push_str("...");
+    }
+
+    format!(
+        "- [{} {}] {} -> {}",
+        turn.created_at.format("%Y-%m-%d"),
+        turn.suggestion_type,
+        response.replace('\n', " "),
+        outcome_note(turn)
+    )
+}
+
 // ============================================================================
 // Internal Models for AI Processing
 // ============================================================================
@@ -150,3 +302,248 @@ pub struct ConversationMessage {
     pub text: String,
     pub timestamp: DateTime<Utc>,
 }
+
+/// The model's reply, as described in the system prompt
+#[derive(Debug, Deserialize)]
+pub struct AiInquiryResponse {
+    pub response: String,
+    pub english_translation: Option<String>,
+    pub reasoning: Option<String>,
+    #[serde(default)]
+    pub negotiation_tips: Vec<String>,
+}
+
+// ============================================================================
+// Languages
+// ============================================================================
+
+/// Languages suggestions can be written in: ISO 639-1 code and English name
+pub const SUPPORTED_LANGUAGES: &[(&str, &str)] = &[
+    ("en", "English"),
+    ("es", "Spanish"),
+    ("fr", "French"),
+    ("de", "German"),
+    ("it", "Italian"),
+    ("pt", "Portuguese"),
+    ("nl", "Dutch"),
+    ("pl", "Polish"),
+    ("ru", "Russian"),
+    ("ar", "Arabic"),
+    ("zh", "Chinese"),
+    ("ja", "Japanese"),
+    ("ko", "Korean"),
+];
+
+/// Supported language for a code such as "es", "ES" or "es-MX"
+pub fn normalize_language(code: &str) -> Option<&'static str> {
+    let primary = code.trim().split(|c| c == '-' || c == '_').next()?.to_lowercase();
+    SUPPORTED_LANGUAGES.iter().map(|(c, _)| *c).find(|c| *c == primary)
+}
+
+pub fn language_name(code: &str) -> &'static str {
+    SUPPORTED_LANGUAGES
+        .iter()
+        .find(|(c, _)| *c == code)
+        .map(|(_, name)| *name)
+        .unwrap_or("English")
+}
+
+/// Common words that are distinctive for each Latin-script language
+const STOPWORDS: &[(&str, &[&str])] = &[
+    ("en", &["the", "and", "is", "are", "we", "you", "for", "with", "this", "that", "have", "please", "would", "thanks", "price"]),
+    ("es", &["el", "los", "las", "que", "por", "para", "con", "una", "usted", "gracias", "precio", "cantidad", "y", "del", "necesitamos"]),
+    ("fr", &["le", "les", "des", "et", "est", "nous", "vous", "pour", "avec", "merci", "prix", "quantité", "bonjour", "pas", "sur"]),
+    ("de", &["der", "die", "das", "und", "ist", "wir", "sie", "nicht", "mit", "für", "ein", "eine", "danke", "preis", "bitte"]),
+    ("it", &["il", "gli", "della", "delle", "sono", "siamo", "grazie", "prezzo", "quantità", "vorremmo", "è", "anche", "questo", "non"]),
+    ("pt", &["não", "você", "obrigado", "obrigada", "preço", "quantidade", "uma", "nós", "os", "também", "estamos", "em"]),
+    ("nl", &["het", "een", "wij", "niet", "voor", "met", "bedankt", "prijs", "hoeveelheid", "graag", "zijn", "ik", "onze"]),
+    ("pl", &["nie", "jest", "się", "że", "dziękuję", "cena", "ilość", "oraz", "czy", "proszę", "dla", "jesteśmy"]),
+];
+
+/// Minimum stopword hits before a Latin-script language is trusted
+const MIN_STOPWORD_HITS: usize = 3;
+
+/// Language of a set of messages: by script for non-Latin text, otherwise by stopwords.
+/// `None` when the text is too short or ambiguous to tell.
+pub fn detect_language(texts: &[&str]) -> Option<&'static str> {
+    let mut letters = 0usize;
+    let (mut hangul, mut kana, mut han, mut cyrillic, mut arabic) = (0usize, 0usize, 0usize, 0usize, 0usize);
+    for c in texts.iter().flat_map(|t| t.chars()).filter(|c| c.is_alphabetic()) {
+        letters += 1;
+        match c as u32 {
+            0xAC00..=0xD7AF | 0x1100..=0x11FF => hangul += 1,
+            0x3040..=0x30FF => kana += 1,
+            0x4E00..=0x9FFF => han += 1,
+            0x0400..=0x04FF => cyrillic += 1,
+            0x0600..=0x06FF => arabic += 1,
+            _ => {}
+        }
+    }
+    if letters == 0 {
+        return None;
+    }
+
+    // Japanese mixes kana with Han characters
+    let (ja, zh) = if kana > 0 { (kana + han, 0) } else { (0, han) };
+    let scripts = [("ko", hangul), ("ja", ja), ("zh", zh), ("ru", cyrillic), ("ar", arabic)];
+    if let Some((code, count)) = scripts.iter().max_by_key(|(_, count)| *count) {
+        if *count * 10 >= letters * 3 {
+            return Some(*code);
+        }
+    }
+
+    let mut scores: Vec<(&'static str, usize)> = STOPWORDS
+        .iter()
+        .map(|(code, words)| {
+            let hits = texts
+                .iter()
+                .flat_map(|t| t.split(|c: char| !c.is_alphabetic()))
+                .filter(|w| !w.is_empty() && words.contains(&w.to_lowercase().as_str()))
+                .count();
+            (*code, hits)
+        })
+        .collect();
+    scores.sort_by(|a, b| b.1.cmp(&a.1));
+
+    match scores.as_slice() {
+        [(code, best), (_, second), ..] if *best >= MIN_STOPWORD_HITS && best > second => Some(*code),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LanguageSource {
+    Request,
+    Profile,
+    MessageHistory,
+    Preference,
+    Default,
+}
+
+impl LanguageSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LanguageSource::Request => "request",
+            LanguageSource::Profile => "profile",
+            LanguageSource::MessageHistory => "message_history",
+            LanguageSource::Preference => "preference",
+            LanguageSource::Default => "default",
+        }
+    }
+}
+
+/// Language to write a suggestion in, in order of precedence: the language asked for in
+/// the request, the counterparty's preferred language, the language of their messages,
+/// the seller's default negotiation language, then English
+pub fn choose_language(
+    requested: Option<&str>,
+    counterparty_preferred: Option<&str>,
+    counterparty_messages: &[&str],
+    negotiation_language: Option<&str>,
+) -> (&'static str, LanguageSource) {
+    if let Some(code) = requested.and_then(normalize_language) {
+        return (code, LanguageSource::Request);
+    }
+    if let Some(code) = counterparty_preferred.and_then(normalize_language) {
+        return (code, LanguageSource::Profile);
+    }
+    if let Some(code) = detect_language(counterparty_messages) {
+        return (code, LanguageSource::MessageHistory);
+    }
+    if let Some(code) = negotiation_language.and_then(normalize_language) {
+        return (code, LanguageSource::Preference);
+    }
+    ("en", LanguageSource::Default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn turn(tokens: i32, outcome: &str) -> AssistantTurn {
+        AssistantTurn {
+            id: Uuid::new_v4(),
+            inquiry_id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            suggestion_id: Uuid::new_v4(),
+            suggestion_type: "negotiation".to_string(),
+            request_text: describe_request("negotiation", None),
+            response_text: "We can offer 5% off for 500 units.".to_string(),
+            outcome: outcome.to_string(),
+            sent_text: None,
+            rejection_reason: Some("Margin too low".to_string()),
+            estimated_tokens: tokens,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_memory_split_keeps_newest_turns_within_budget() {
+        let turns = vec![turn(1000, "rejected"), turn(1500, "accepted"), turn(1000, "pending")];
+
+        assert_eq!(memory_split(&turns, 3000), 1);
+        assert_eq!(memory_split(&turns, 10_000), 0);
+        // The newest turn alone is over budget: everything is condensed
+        assert_eq!(memory_split(&turns, 500), 3);
+        assert_eq!(memory_split(&[], 3000), 0);
+    }
+
+    #[test]
+    fn test_outcome_notes_and_condensing() {
+        assert_eq!(outcome_note(&turn(10, "rejected")), "The seller rejected this suggestion. Reason: Margin too low");
+        assert_eq!(outcome_note(&turn(10, "accepted")), "The seller sent this suggestion unchanged.");
+
+        let mut long = turn(10, "pending");
+        long.response_text = "x".repeat(500);
+        let line = condense_turn(&long);
+        assert!(line.contains("negotiation"));
+        assert!(line.contains(&format!("{}...", "x".repeat(200))));
+        assert!(line.ends_with("The seller has not used this suggestion."));
+
+        assert_eq!(
+            describe_request("pricing_adjustment", Some(" offer 10% ")),
+            "Suggest a pricing adjustment response. Seller instructions: offer 10%"
+        );
+        assert_eq!(estimate_tokens("abcdefgh"), 2);
+    }
+
+    #[test]
+    fn test_normalize_language() {
+        assert_eq!(normalize_language("es-MX"), Some("es"));
+        assert_eq!(normalize_language(" DE "), Some("de"));
+        assert_eq!(normalize_language("pt_BR"), Some("pt"));
+        assert_eq!(normalize_language("xx"), None);
+    }
+
+    #[test]
+    fn test_detect_language() {
+        assert_eq!(
+            detect_language(&["Hello, we would like to order 200 units. What is the price for this batch?"]),
+            Some("en")
+        );
+        assert_eq!(
+            detect_language(&["Hola, necesitamos 200 unidades. ¿Cuál es el precio por unidad para el lote?"]),
+            Some("es")
+        );
+        assert_eq!(
+            detect_language(&["Guten Tag, wir brauchen 200 Einheiten. Ist der Preis für die Charge verhandelbar?"]),
+            Some("de")
+        );
+        assert_eq!(detect_language(&["Здравствуйте, нам нужно 200 упаковок."]), Some("ru"));
+        assert_eq!(detect_language(&["こんにちは、200個を注文したいです。"]), Some("ja"));
+        assert_eq!(detect_language(&["OK"]), None);
+        assert_eq!(detect_language(&[]), None);
+    }
+
+    #[test]
+    fn test_choose_language_precedence() {
+        let spanish = ["Hola, necesitamos 200 unidades. ¿Cuál es el precio por unidad para el lote?"];
+
+        assert_eq!(choose_language(Some("fr"), Some("de"), &spanish, Some("it")), ("fr", LanguageSource::Request));
+        assert_eq!(choose_language(None, Some("de"), &spanish, Some("it")), ("de", LanguageSource::Profile));
+        assert_eq!(choose_language(None, None, &spanish, Some("it")), ("es", LanguageSource::MessageHistory));
+        assert_eq!(choose_language(None, None, &["OK"], Some("it")), ("it", LanguageSource::Preference));
+        assert_eq!(choose_language(Some("xx"), None, &[], None), ("en", LanguageSource::Default));
+    }
+}
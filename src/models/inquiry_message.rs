@@ -1,5 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use sqlx::FromRow;
 use uuid::Uuid;
 
@@ -12,13 +13,13 @@ pub struct InquiryMessage {
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateInquiryMessageRequest {
     pub inquiry_id: Uuid,
     pub message: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct InquiryMessageResponse {
     pub id: Uuid,
     pub inquiry_id: Uuid,
@@ -1,5 +1,6 @@
 use chrono::{DateTime, Utc, NaiveDate};
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 use sqlx::FromRow;
 use uuid::Uuid;
 use validator::{Validate, ValidationError};
@@ -45,7 +46,7 @@ pub struct InventoryWithDetails {
     pub user: UserResponse,
 }
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct CreateInventoryRequest {
     pub pharmaceutical_id: Uuid,
     #[validate(length(min = 1, message = "Batch number required"))]
@@ -59,7 +60,7 @@ pub struct CreateInventoryRequest {
     pub storage_location: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
 pub struct UpdateInventoryRequest {
     #[validate(range(min = 0, message = "Quantity cannot be negative"))]
     pub quantity: Option<i32>,
@@ -71,8 +72,11 @@ pub struct UpdateInventoryRequest {
     pub status: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Serialize, Deserialize, Validate, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct SearchInventoryRequest {
+    /// Free text over product, manufacturer and seller names (typo-tolerant with a search engine)
+    pub q: Option<String>,
     pub pharmaceutical_id: Option<Uuid>,
     pub brand_name: Option<String>,
     pub generic_name: Option<String>,
@@ -85,13 +89,19 @@ pub struct SearchInventoryRequest {
     pub status: Option<String>,
     pub min_price: Option<rust_decimal::Decimal>,
     pub max_price: Option<rust_decimal::Decimal>,
+    pub product_domain: Option<crate::models::pharmaceutical::ProductDomain>,
+    /// Comma-separated allergen classes to exclude, e.g. `lactose-free,gelatin-free`
+    pub free_of: Option<String>,
+    /// Comma-separated fields to count values of, e.g. `manufacturer,dosage_form`; the
+    /// response then wraps the results with the counts
+    pub facets: Option<String>,
     pub limit: Option<i64>,
     pub offset: Option<i64>,
     pub sort_by: Option<String>,
     pub sort_order: Option<String>,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, ToSchema)]
 pub struct InventoryResponse {
     pub id: Uuid,
     pub pharmaceutical: PharmaceuticalResponse,
@@ -107,12 +117,13 @@ pub struct InventoryResponse {
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct ExpiryAlertRequest {
     pub days_threshold: i64,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ExpiryAlert {
     pub inventory_id: Uuid,
     pub pharmaceutical_name: String,
@@ -0,0 +1,308 @@
+/// Regulatory knowledge-base management models (RAG entries, sources and versions)
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Document types an entry can be retrieved for
+pub const KNOWLEDGE_DOCUMENT_TYPES: [&str; 4] = ["CoA", "GDP", "GMP", "general"];
+
+/// Source that versions entries stored without a regulation source (seeded data)
+pub const UNSPECIFIED_SOURCE: &str = "Unspecified source";
+
+/// Target chunk size for bulk imports, in characters
+pub const DEFAULT_CHUNK_CHARS: usize = 1500;
+pub const MIN_CHUNK_CHARS: usize = 200;
+pub const MAX_CHUNK_CHARS: usize = 6000;
+
+// ============================================================================
+// Database Models
+// ============================================================================
+
+/// Knowledge-base entry without its embedding
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct KnowledgeBaseEntry {
+    pub id: Uuid,
+    pub document_type: String,
+    pub regulation_source: Option<String>,
+    pub regulation_section: Option<String>,
+    pub section_title: String,
+    pub content: String,
+    pub metadata: serde_json::Value,
+    pub source_version: i32,
+    pub created_by: Option<Uuid>,
+    pub updated_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct KnowledgeSource {
+    pub regulation_source: String,
+    pub current_version: i32,
+    pub entry_count: i64,
+    pub updated_by: Option<Uuid>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct KnowledgeSourceVersion {
+    pub regulation_source: String,
+    pub version: i32,
+    pub change_type: String,
+    pub entries_changed: i32,
+    pub notes: Option<String>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct KnowledgeEntryVersion {
+    pub id: i64,
+    pub entry_id: Uuid,
+    pub regulation_source: String,
+    pub source_version: i32,
+    pub change_type: String,
+    pub document_type: String,
+    pub regulation_section: Option<String>,
+    pub section_title: String,
+    pub content: String,
+    pub metadata: serde_json::Value,
+    pub changed_by: Option<Uuid>,
+    pub changed_at: DateTime<Utc>,
+}
+
+// ============================================================================
+// API Request/Response Models
+// ============================================================================
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListKnowledgeEntriesQuery {
+    pub document_type: Option<String>,
+    pub regulation_source: Option<String>,
+    /// Case-insensitive match on title and content
+    pub search: Option<String>,
+    pub page: Option<i64>,
+    pub page_size: Option<i64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct KnowledgeEntryListResponse {
+    pub entries: Vec<KnowledgeBaseEntry>,
+    pub total: i64,
+    pub page: i64,
+    pub page_size: i64,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct CreateKnowledgeEntryRequest {
+    pub document_type: String,
+    #[validate(length(min = 1, max = 200))]
+    pub regulation_source: String,
+    #[validate(length(max = 100))]
+    pub regulation_section: Option<String>,
+    #[validate(length(min = 1, max = 500))]
+    pub section_title: String,
+    #[validate(length(min = 1, max = 20000))]
+    pub content: String,
+    pub metadata: Option<serde_json::Value>,
+}
+
+/// Fields left out are unchanged; the entry is re-embedded when its content changes
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct UpdateKnowledgeEntryRequest {
+    pub document_type: Option<String>,
+    #[validate(length(max = 100))]
+    pub regulation_section: Option<String>,
+    #[validate(length(min = 1, max = 500))]
+    pub section_title: Option<String>,
+    #[validate(length(min = 1, max = 20000))]
+    pub content: Option<String>,
+    pub metadata: Option<serde_json::Value>,
+}
+
+/// Form fields of a bulk import upload (the file itself arrives as `file`)
+#[derive(Debug, Default)]
+pub struct KnowledgeImportOptions {
+    pub document_type: String,
+    pub regulation_source: String,
+    pub regulation_section: Option<String>,
+    /// Replace the source's existing entries instead of adding to them
+    pub replace_existing: bool,
+    pub chunk_chars: Option<usize>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct KnowledgeImportResult {
+    pub regulation_source: String,
+    pub source_version: i32,
+    pub entries_created: usize,
+    pub entries_replaced: usize,
+    pub entries: Vec<KnowledgeBaseEntry>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SourceVersionsQuery {
+    pub regulation_source: String,
+}
+
+pub fn validate_document_type(document_type: &str) -> Result<(), String> {
+    if KNOWLEDGE_DOCUMENT_TYPES.contains(&document_type) {
+        Ok(())
+    } else {
+        Err(format!(
+            "document_type must be one of {}",
+            KNOWLEDGE_DOCUMENT_TYPES.join(", ")
+        ))
+    }
+}
+
+// ============================================================================
+// Chunking
+// ============================================================================
+
+/// A piece of an imported document, stored as one knowledge-base entry
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocumentChunk {
+    /// Closest heading above the chunk, if the document has headings
+    pub heading: Option<String>,
+    pub content: String,
+}
+
+/// Markdown headings ("## Storage") and numbered section titles ("3.2 Storage conditions")
+fn heading_text(line: &str) -> Option<String> {
+    let line = line.trim();
+    if line.starts_with('#') {
+        let text = line.trim_start_matches('#').trim();
+        return (!text.is_empty()).then(|| text.to_string());
+    }
+
+    let (number, rest) = line.split_once(' ')?;
+    let is_section_number = !number.is_empty()
+        && number.chars().next().map_or(false, |c| c.is_ascii_digit())
+        && number.trim_end_matches('.').chars().all(|c| c.is_ascii_digit() || c == '.');
+    let is_title = line.chars().count() <= 120 && !line.ends_with('.') && !rest.trim().is_empty();
+
+    (is_section_number && is_title).then(|| line.to_string())
+}
+
+/// Split text at the last sentence end (or whitespace) before `max_chars` characters
+fn split_long(text: &str, max_chars: usize) -> Vec<String> {
+    let mut pieces = Vec::new();
+    let mut rest = text.trim();
+
+    while rest.chars().count() > max_chars {
+        let limit = rest.char_indices().nth(max_chars).map_or(rest.len(), |(i, _)| i);
+        let window = &rest[..limit];
+        let cut = window
+            .rfind(". ")
+            .map(|i| i + 1)
+            .or_else(|| window.rfind(char::is_whitespace))
+            .filter(|&i| i > 0)
+            .unwrap_or(limit);
+
+        pieces.push(rest[..cut].trim().to_string());
+        rest = rest[cut..].trim_start();
+    }
+    if !rest.is_empty() {
+        pieces.push(rest.to_string());
+    }
+
+    pieces
+}
+
+/// Split a regulation into chunks of at most `max_chars` characters. Paragraphs are kept
+/// together where possible, and a new heading always starts a new chunk so each chunk
+/// stays within one section.
+pub fn chunk_document(text: &str, max_chars: usize) -> Vec<DocumentChunk> {
+    let max_chars = max_chars.clamp(MIN_CHUNK_CHARS, MAX_CHUNK_CHARS);
+    let mut chunks = Vec::new();
+    let mut heading: Option<String> = None;
+    let mut current = String::new();
+
+    fn flush(chunks: &mut Vec<DocumentChunk>, heading: &Option<String>, current: &mut String) {
+        if !current.trim().is_empty() {
+            chunks.push(DocumentChunk {
+                heading: heading.clone(),
+                content: current.trim().to_string(),
+            });
+        }
+        current.clear();
+    }
+
+    let normalized = text.replace("\r\n", "\n");
+    for paragraph in normalized.split("\n\n").map(str::trim).filter(|p| !p.is_empty()) {
+        let mut lines = paragraph.lines();
+        let mut body = paragraph.to_string();
+
+        if let Some(title) = lines.next().and_then(heading_text) {
+            flush(&mut chunks, &heading, &mut current);
+            heading = Some(title);
+            body = lines.collect::<Vec<_>>().join("\n");
+            if body.trim().is_empty() {
+                continue;
+            }
+        }
+
+        for piece in split_long(&body, max_chars) {
+            let separator = if current.is_empty() { 0 } else { 2 };
+            if current.chars().count() + separator + piece.chars().count() > max_chars {
+                flush(&mut chunks, &heading, &mut current);
+            }
+            if !current.is_empty() {
+                current.push_str("\n\n");
+            }
+            current.push_str(&piece);
+        }
+    }
+    flush(&mut chunks, &heading, &mut current);
+
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunks_follow_headings() {
+        let text = "# Storage\n\nStore between 2 and 8 °C.\n\nMonitor temperature continuously.\n\n\
+                    ## Transport\n\nUse qualified vehicles.\n\n3.2 Returns handling\nReturned goods are quarantined.";
+        let chunks = chunk_document(text, DEFAULT_CHUNK_CHARS);
+
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[0].heading.as_deref(), Some("Storage"));
+        assert_eq!(chunks[0].content, "Store between 2 and 8 °C.\n\nMonitor temperature continuously.");
+        assert_eq!(chunks[1].heading.as_deref(), Some("Transport"));
+        assert_eq!(chunks[2].heading.as_deref(), Some("3.2 Returns handling"));
+        assert_eq!(chunks[2].content, "Returned goods are quarantined.");
+    }
+
+    #[test]
+    fn test_long_paragraphs_are_split_at_sentences() {
+        let sentence = "Each batch shall be tested against its specification. ";
+        let text = sentence.repeat(20);
+        let chunks = chunk_document(&text, MIN_CHUNK_CHARS);
+
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|c| c.content.chars().count() <= MIN_CHUNK_CHARS));
+        assert!(chunks.iter().all(|c| c.content.ends_with('.')));
+        assert!(chunks.iter().all(|c| c.heading.is_none()));
+    }
+
+    #[test]
+    fn test_heading_detection() {
+        assert_eq!(heading_text("## 4. Premises").as_deref(), Some("4. Premises"));
+        assert_eq!(heading_text("211.160 General requirements").as_deref(), Some("211.160 General requirements"));
+        assert_eq!(heading_text("12 batches were released on time."), None);
+        assert_eq!(heading_text("Storage conditions"), None);
+        assert!(validate_document_type("GDP").is_ok());
+        assert!(validate_document_type("SOP").is_err());
+    }
+}
@@ -0,0 +1,240 @@
+/// Legal hold models: holds on a user (company), a set of transactions or a whole data
+/// category, which exempt data from retention purges and block deletion until released
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+use super::data_retention::RetentionCategory;
+
+/// Transactions a single hold may cover
+pub const MAX_HELD_TRANSACTIONS: usize = 500;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LegalHoldTarget {
+    /// All data of the category (or of every category)
+    All,
+    User,
+    Transactions,
+}
+
+impl LegalHoldTarget {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LegalHoldTarget::All => "all",
+            LegalHoldTarget::User => "user",
+            LegalHoldTarget::Transactions => "transactions",
+        }
+    }
+}
+
+// ============================================================================
+// DATABASE MODELS
+// ============================================================================
+
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct LegalHold {
+    pub id: Uuid,
+    pub target_type: String,
+    /// NULL: every category
+    pub category: Option<String>,
+    pub user_id: Option<Uuid>,
+    pub transaction_ids: Option<Vec<Uuid>>,
+    pub subject_name: Option<String>,
+    pub reason: String,
+    pub reference: Option<String>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub released_at: Option<DateTime<Utc>>,
+    pub released_by: Option<Uuid>,
+    pub release_reason: Option<String>,
+}
+
+impl LegalHold {
+    pub fn is_active(&self) -> bool {
+        self.released_at.is_none()
+    }
+
+    pub fn covers(&self, category: RetentionCategory) -> bool {
+        self.is_active() && self.category.as_deref().map_or(true, |c| c == category.as_str())
+    }
+}
+
+// ============================================================================
+// REQUEST / RESPONSE MODELS
+// ============================================================================
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct CreateLegalHoldRequest {
+    /// Hold this user's (company's) data
+    pub user_id: Option<Uuid>,
+    /// Hold the data of these transactions
+    pub transaction_ids: Option<Vec<Uuid>>,
+    /// Limit the hold to one data category; omit to hold every category
+    pub category: Option<RetentionCategory>,
+    #[validate(length(min = 1, max = 2000, message = "Reason must be 1-2000 characters"))]
+    pub reason: String,
+    /// Case or matter reference
+    #[validate(length(max = 255, message = "Reference must be at most 255 characters"))]
+    pub reference: Option<String>,
+}
+
+impl CreateLegalHoldRequest {
+    /// What the hold is placed on; a hold targets a user or transactions, not both
+    pub fn target(&self) -> Result<LegalHoldTarget, String> {
+        match (&self.user_id, &self.transaction_ids) {
+            (Some(_), Some(_)) => {
+                Err("A legal hold is placed on a user or on transactions, not both".to_string())
+            }
+            (Some(_), None) => Ok(LegalHoldTarget::User),
+            (None, Some(ids)) if ids.is_empty() => {
+                Err("transaction_ids must list at least one transaction".to_string())
+            }
+            (None, Some(ids)) if ids.len() > MAX_HELD_TRANSACTIONS => Err(format!(
+                "A legal hold covers at most {} transactions",
+                MAX_HELD_TRANSACTIONS
+            )),
+            (None, Some(_)) => Ok(LegalHoldTarget::Transactions),
+            (None, None) => Ok(LegalHoldTarget::All),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct ReleaseLegalHoldRequest {
+    #[validate(length(min = 1, max = 2000, message = "Reason must be 1-2000 characters"))]
+    pub reason: String,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct LegalHoldQuery {
+    #[serde(default)]
+    pub include_released: bool,
+    pub user_id: Option<Uuid>,
+}
+
+// ============================================================================
+// HOLD SCOPE
+// ============================================================================
+
+/// What active holds keep out of a category's purge
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HoldScope {
+    /// The whole category is held
+    pub whole_category: bool,
+    pub user_ids: Vec<Uuid>,
+    pub transaction_ids: Vec<Uuid>,
+}
+
+impl HoldScope {
+    pub fn is_empty(&self) -> bool {
+        !self.whole_category && self.user_ids.is_empty() && self.transaction_ids.is_empty()
+    }
+}
+
+/// Combine the active holds that cover a category
+pub fn hold_scope(holds: &[LegalHold], category: RetentionCategory) -> HoldScope {
+    let mut scope = HoldScope::default();
+    for hold in holds.iter().filter(|h| h.covers(category)) {
+        match hold.target_type.as_str() {
+            "user" => {
+                if let Some(user_id) = hold.user_id {
+                    if !scope.user_ids.contains(&user_id) {
+                        scope.user_ids.push(user_id);
+                    }
+                }
+            }
+            "transactions" => {
+                for id in hold.transaction_ids.iter().flatten() {
+                    if !scope.transaction_ids.contains(id) {
+                        scope.transaction_ids.push(*id);
+                    }
+                }
+            }
+            _ => scope.whole_category = true,
+        }
+    }
+    scope
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hold(category: Option<&str>, user_id: Option<Uuid>, transaction_ids: Option<Vec<Uuid>>) -> LegalHold {
+        let target_type = match (&user_id, &transaction_ids) {
+            (Some(_), _) => "user",
+            (None, Some(_)) => "transactions",
+            (None, None) => "all",
+        };
+        LegalHold {
+            id: Uuid::new_v4(),
+            target_type: target_type.to_string(),
+            category: category.map(str::to_string),
+            user_id,
+            transaction_ids,
+            subject_name: None,
+            reason: "Litigation".to_string(),
+            reference: None,
+            created_by: None,
+            created_at: Utc::now(),
+            released_at: None,
+            released_by: None,
+            release_reason: None,
+        }
+    }
+
+    #[test]
+    fn test_hold_scope() {
+        let user = Uuid::new_v4();
+        let other = Uuid::new_v4();
+        let transaction = Uuid::new_v4();
+
+        assert!(hold_scope(&[], RetentionCategory::Messages).is_empty());
+
+        let holds = vec![
+            hold(Some("messages"), Some(user), None),
+            hold(None, Some(other), None),
+            hold(Some("messages"), Some(user), None),
+            hold(None, None, Some(vec![transaction])),
+        ];
+        let messages = hold_scope(&holds, RetentionCategory::Messages);
+        assert_eq!(messages.user_ids, vec![user, other]);
+        assert_eq!(messages.transaction_ids, vec![transaction]);
+        assert!(!messages.whole_category);
+        assert_eq!(hold_scope(&holds, RetentionCategory::AuditLogs).user_ids, vec![other]);
+
+        let mut released = hold(Some("sync_logs"), None, None);
+        released.released_at = Some(Utc::now());
+        assert!(hold_scope(&[released], RetentionCategory::SyncLogs).is_empty());
+
+        let category_wide = vec![hold(Some("sync_logs"), Some(user), None), hold(Some("sync_logs"), None, None)];
+        assert!(hold_scope(&category_wide, RetentionCategory::SyncLogs).whole_category);
+        assert!(hold_scope(&category_wide, RetentionCategory::Notifications).is_empty());
+    }
+
+    #[test]
+    fn test_hold_target() {
+        let request = |user_id: Option<Uuid>, transaction_ids: Option<Vec<Uuid>>| CreateLegalHoldRequest {
+            user_id,
+            transaction_ids,
+            category: None,
+            reason: "Subpoena".to_string(),
+            reference: None,
+        };
+
+        assert_eq!(request(None, None).target(), Ok(LegalHoldTarget::All));
+        assert_eq!(request(Some(Uuid::new_v4()), None).target(), Ok(LegalHoldTarget::User));
+        assert_eq!(
+            request(None, Some(vec![Uuid::new_v4()])).target(),
+            Ok(LegalHoldTarget::Transactions)
+        );
+        assert!(request(None, Some(vec![])).target().is_err());
+        assert!(request(Some(Uuid::new_v4()), Some(vec![Uuid::new_v4()])).target().is_err());
+    }
+}
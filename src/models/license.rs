@@ -0,0 +1,471 @@
+/// License registry models: the licenses and certifications a company trades under,
+/// their admin review, expiry reminders and the listing suspension of expired licenses
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Days before expiry at which a reminder is sent, largest first
+pub const LICENSE_REMINDER_DAYS: [i32; 3] = [60, 30, 7];
+
+/// Maximum accepted size of an attached license document (15 MB)
+pub const MAX_LICENSE_DOCUMENT_SIZE: usize = 15 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LicenseType {
+    WholesaleDistributionAuthorization,
+    GdpCertificate,
+    DeaRegistration,
+    StateLicense,
+    ImportLicense,
+    /// Export/import permit for narcotics and psychotropic substances
+    NarcoticsPermit,
+    Other,
+}
+
+impl LicenseType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LicenseType::WholesaleDistributionAuthorization => "wholesale_distribution_authorization",
+            LicenseType::GdpCertificate => "gdp_certificate",
+            LicenseType::DeaRegistration => "dea_registration",
+            LicenseType::StateLicense => "state_license",
+            LicenseType::ImportLicense => "import_license",
+            LicenseType::NarcoticsPermit => "narcotics_permit",
+            LicenseType::Other => "other",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            LicenseType::WholesaleDistributionAuthorization => "Wholesale distribution authorization",
+            LicenseType::GdpCertificate => "GDP certificate",
+            LicenseType::DeaRegistration => "DEA registration",
+            LicenseType::StateLicense => "State license",
+            LicenseType::ImportLicense => "Import license",
+            LicenseType::NarcoticsPermit => "Narcotics permit",
+            LicenseType::Other => "License",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "wholesale_distribution_authorization" => Some(LicenseType::WholesaleDistributionAuthorization),
+            "gdp_certificate" => Some(LicenseType::GdpCertificate),
+            "dea_registration" => Some(LicenseType::DeaRegistration),
+            "state_license" => Some(LicenseType::StateLicense),
+            "import_license" => Some(LicenseType::ImportLicense),
+            "narcotics_permit" => Some(LicenseType::NarcoticsPermit),
+            "other" => Some(LicenseType::Other),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum LicenseStatus {
+    Pending,
+    Verified,
+    Rejected,
+    Revoked,
+}
+
+impl LicenseStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LicenseStatus::Pending => "pending",
+            LicenseStatus::Verified => "verified",
+            LicenseStatus::Rejected => "rejected",
+            LicenseStatus::Revoked => "revoked",
+        }
+    }
+}
+
+// ============================================================================
+// DATABASE MODELS
+// ============================================================================
+
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct BusinessLicense {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub license_type: String,
+    pub license_number: String,
+    pub issuing_authority: String,
+    pub country_code: Option<String>,
+    pub issued_on: Option<NaiveDate>,
+    /// None: does not expire
+    pub expires_on: Option<NaiveDate>,
+    pub status: String,
+    pub reviewed_by: Option<Uuid>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub review_notes: Option<String>,
+    #[serde(skip_serializing)]
+    pub document_path: String,
+    pub document_filename: String,
+    pub document_content_type: String,
+    pub document_size_bytes: i64,
+    pub document_hash: String,
+    pub last_reminder_days: Option<i32>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl BusinessLicense {
+    pub fn is_verified(&self) -> bool {
+        self.status == LicenseStatus::Verified.as_str()
+    }
+
+    /// Days until expiry (negative once expired); None for licenses that don't expire
+    pub fn days_to_expiry(&self, today: NaiveDate) -> Option<i64> {
+        self.expires_on.map(|expires_on| (expires_on - today).num_days())
+    }
+
+    /// Expired licenses are no longer valid on their expiry date
+    pub fn is_expired(&self, today: NaiveDate) -> bool {
+        self.expires_on.map_or(false, |expires_on| expires_on <= today)
+    }
+
+    pub fn type_label(&self) -> &'static str {
+        LicenseType::parse(&self.license_type).map_or("License", |t| t.label())
+    }
+}
+
+// ============================================================================
+// REQUEST / RESPONSE MODELS
+// ============================================================================
+
+/// Sent as multipart text fields alongside the `file` field
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateLicenseRequest {
+    pub license_type: LicenseType,
+    #[validate(length(min = 1, max = 100, message = "License number must be 1-100 characters"))]
+    pub license_number: String,
+    #[validate(length(min = 1, max = 255, message = "Issuing authority must be 1-255 characters"))]
+    pub issuing_authority: String,
+    /// ISO 3166-1 alpha-2
+    pub country_code: Option<String>,
+    pub issued_on: Option<NaiveDate>,
+    /// Omit for licenses that don't expire
+    pub expires_on: Option<NaiveDate>,
+}
+
+impl CreateLicenseRequest {
+    /// Normalize the country code and check the validity period
+    pub fn normalize(mut self, today: NaiveDate) -> Result<Self, String> {
+        self.license_number = self.license_number.trim().to_string();
+        self.issuing_authority = self.issuing_authority.trim().to_string();
+        if self.license_number.is_empty() || self.issuing_authority.is_empty() {
+            return Err("License number and issuing authority are required".to_string());
+        }
+
+        self.country_code = match self.country_code.as_deref().map(str::trim) {
+            None | Some("") => None,
+            Some(code) => {
+                let code = code.to_ascii_uppercase();
+                if code.len() != 2 || !code.chars().all(|c| c.is_ascii_alphabetic()) {
+                    return Err(format!("Invalid country code '{}'", code));
+                }
+                Some(code)
+            }
+        };
+
+        if let (Some(issued_on), Some(expires_on)) = (self.issued_on, self.expires_on) {
+            if issued_on >= expires_on {
+                return Err("A license must expire after it was issued".to_string());
+            }
+        }
+        if self.issued_on.map_or(false, |issued_on| issued_on > today) {
+            return Err("Issue date can't be in the future".to_string());
+        }
+        if self.expires_on.map_or(false, |expires_on| expires_on <= today) {
+            return Err("This license has already expired; upload the renewed license".to_string());
+        }
+
+        Ok(self)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum LicenseReviewDecision {
+    Verify,
+    Reject,
+    /// Withdraw an earlier verification, e.g. when the authority revoked the license
+    Revoke,
+}
+
+impl LicenseReviewDecision {
+    pub fn resulting_status(&self) -> LicenseStatus {
+        match self {
+            LicenseReviewDecision::Verify => LicenseStatus::Verified,
+            LicenseReviewDecision::Reject => LicenseStatus::Rejected,
+            LicenseReviewDecision::Revoke => LicenseStatus::Revoked,
+        }
+    }
+
+    /// Status a license must have for the decision to apply
+    pub fn applies_to(&self, status: &str) -> bool {
+        match self {
+            LicenseReviewDecision::Verify | LicenseReviewDecision::Reject => status == LicenseStatus::Pending.as_str(),
+            LicenseReviewDecision::Revoke => status == LicenseStatus::Verified.as_str(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct ReviewLicenseRequest {
+    pub decision: LicenseReviewDecision,
+    /// Required when rejecting or revoking
+    #[validate(length(max = 2000, message = "Notes must be at most 2000 characters"))]
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct LicenseQuery {
+    pub status: Option<LicenseStatus>,
+    pub license_type: Option<LicenseType>,
+    pub user_id: Option<Uuid>,
+    /// Verified licenses expiring within this many days (expired ones included)
+    pub expiring_within_days: Option<i32>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// A company's licenses and whether it may list inventory
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LicenseRegistryResponse {
+    pub listing_suspended: bool,
+    pub listing_suspended_at: Option<DateTime<Utc>>,
+    pub listing_suspension_reason: Option<String>,
+    pub licenses: Vec<BusinessLicense>,
+}
+
+// ============================================================================
+// EXPIRY RULES
+// ============================================================================
+
+/// Reminder threshold due for a license `days_to_expiry` days from expiring, given the
+/// smallest threshold already sent. Each threshold is sent once, and only the smallest
+/// one reached when several are (e.g. after downtime or a late verification).
+pub fn reminder_due(days_to_expiry: i64, last_reminder_days: Option<i32>) -> Option<i32> {
+    if days_to_expiry < 0 {
+        return None;
+    }
+    let threshold = LICENSE_REMINDER_DAYS
+        .iter()
+        .copied()
+        .filter(|&days| days_to_expiry <= days as i64)
+        .min()?;
+
+    match last_reminder_days {
+        Some(sent) if sent <= threshold => None,
+        _ => Some(threshold),
+    }
+}
+
+/// Why a company's listing rights are suspended, if they are: some license type has
+/// verified licenses but all of them have expired. Pending renewals don't count until
+/// verified, and rejected or revoked licenses are ignored.
+pub fn listing_suspension_reason(licenses: &[BusinessLicense], today: NaiveDate) -> Option<String> {
+    let mut lapsed: Vec<&BusinessLicense> = Vec::new();
+
+    for license in licenses.iter().filter(|l| l.is_verified()) {
+        let same_type = licenses
+            .iter()
+            .filter(|l| l.is_verified() && l.license_type == license.license_type);
+        let has_valid = same_type.clone().any(|l| !l.is_expired(today));
+        if has_valid || lapsed.iter().any(|l| l.license_type == license.license_type) {
+            continue;
+        }
+        // Report the most recently expired license of the type
+        if let Some(latest) = same_type.max_by_key(|l| l.expires_on) {
+            lapsed.push(latest);
+        }
+    }
+
+    if lapsed.is_empty() {
+        return None;
+    }
+
+    let expired: Vec<String> = lapsed
+        .iter()
+        .map(|l| {
+            format!(
+                "{} {} expired on {}",
+                l.type_label(),
+                l.license_number,
+                l.expires_on.map(|d| d.to_string()).unwrap_or_default()
+            )
+        })
+        .collect();
+    Some(format!("Expired license: {}", expired.join("; ")))
+}
+
+/// Content type of a license document, detected from its magic bytes (PDF, PNG, JPEG)
+pub fn document_content_type(data: &[u8]) -> Option<&'static str> {
+    if data.starts_with(b"%PDF-") {
+        Some("application/pdf")
+    } else if data.starts_with(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]) {
+        Some("image/png")
+    } else if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn license(license_type: LicenseType, status: LicenseStatus, expires_on: Option<&str>) -> BusinessLicense {
+        BusinessLicense {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            license_type: license_type.as_str().to_string(),
+            license_number: "RA-1234".to_string(),
+            issuing_authority: "State Board of Pharmacy".to_string(),
+            country_code: Some("US".to_string()),
+            issued_on: None,
+            expires_on: expires_on.map(date),
+            status: status.as_str().to_string(),
+            reviewed_by: None,
+            reviewed_at: None,
+            review_notes: None,
+            document_path: "x/license.pdf".to_string(),
+            document_filename: "license.pdf".to_string(),
+            document_content_type: "application/pdf".to_string(),
+            document_size_bytes: 1024,
+            document_hash: String::new(),
+            last_reminder_days: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_reminder_thresholds() {
+        assert_eq!(reminder_due(90, None), None);
+        assert_eq!(reminder_due(60, None), Some(60));
+        assert_eq!(reminder_due(45, Some(60)), None);
+        assert_eq!(reminder_due(30, Some(60)), Some(30));
+        assert_eq!(reminder_due(7, Some(30)), Some(7));
+        assert_eq!(reminder_due(0, Some(7)), None);
+        assert_eq!(reminder_due(-1, None), None);
+    }
+
+    #[test]
+    fn test_reminder_skips_to_smallest_threshold_reached() {
+        assert_eq!(reminder_due(5, None), Some(7));
+        assert_eq!(reminder_due(20, Some(60)), Some(30));
+    }
+
+    #[test]
+    fn test_expired_verified_license_suspends() {
+        let today = date("2026-06-01");
+        let licenses = vec![license(LicenseType::DeaRegistration, LicenseStatus::Verified, Some("2026-06-01"))];
+
+        let reason = listing_suspension_reason(&licenses, today).unwrap();
+        assert!(reason.contains("DEA registration RA-1234 expired on 2026-06-01"));
+    }
+
+    #[test]
+    fn test_verified_renewal_lifts_suspension() {
+        let today = date("2026-06-01");
+        let licenses = vec![
+            license(LicenseType::DeaRegistration, LicenseStatus::Verified, Some("2026-05-01")),
+            license(LicenseType::DeaRegistration, LicenseStatus::Verified, Some("2029-05-01")),
+        ];
+        assert_eq!(listing_suspension_reason(&licenses, today), None);
+    }
+
+    #[test]
+    fn test_pending_renewal_does_not_lift_suspension() {
+        let today = date("2026-06-01");
+        let licenses = vec![
+            license(LicenseType::GdpCertificate, LicenseStatus::Verified, Some("2026-05-01")),
+            license(LicenseType::GdpCertificate, LicenseStatus::Pending, Some("2029-05-01")),
+        ];
+        assert!(listing_suspension_reason(&licenses, today).is_some());
+    }
+
+    #[test]
+    fn test_unverified_and_non_expiring_licenses_never_suspend() {
+        let today = date("2026-06-01");
+        let licenses = vec![
+            license(LicenseType::GdpCertificate, LicenseStatus::Pending, Some("2026-01-01")),
+            license(LicenseType::StateLicense, LicenseStatus::Rejected, Some("2026-01-01")),
+            license(LicenseType::DeaRegistration, LicenseStatus::Revoked, Some("2026-01-01")),
+            license(LicenseType::WholesaleDistributionAuthorization, LicenseStatus::Verified, None),
+        ];
+        assert_eq!(listing_suspension_reason(&licenses, today), None);
+    }
+
+    #[test]
+    fn test_suspension_reason_lists_each_lapsed_type_once() {
+        let today = date("2026-06-01");
+        let licenses = vec![
+            license(LicenseType::DeaRegistration, LicenseStatus::Verified, Some("2025-05-01")),
+            license(LicenseType::DeaRegistration, LicenseStatus::Verified, Some("2026-05-01")),
+            license(LicenseType::StateLicense, LicenseStatus::Verified, Some("2026-03-01")),
+        ];
+
+        let reason = listing_suspension_reason(&licenses, today).unwrap();
+        assert_eq!(reason.matches("DEA registration").count(), 1);
+        assert!(reason.contains("2026-05-01"));
+        assert!(reason.contains("State license"));
+    }
+
+    #[test]
+    fn test_create_request_normalization() {
+        let today = date("2026-06-01");
+        let request = CreateLicenseRequest {
+            license_type: LicenseType::GdpCertificate,
+            license_number: " GDP-42 ".to_string(),
+            issuing_authority: "BfArM".to_string(),
+            country_code: Some("de".to_string()),
+            issued_on: Some(date("2025-01-01")),
+            expires_on: Some(date("2028-01-01")),
+        };
+        let request = request.normalize(today).unwrap();
+        assert_eq!(request.license_number, "GDP-42");
+        assert_eq!(request.country_code.as_deref(), Some("DE"));
+
+        let expired = CreateLicenseRequest {
+            license_type: LicenseType::GdpCertificate,
+            license_number: "GDP-42".to_string(),
+            issuing_authority: "BfArM".to_string(),
+            country_code: None,
+            issued_on: None,
+            expires_on: Some(today),
+        };
+        assert!(expired.normalize(today).is_err());
+    }
+
+    #[test]
+    fn test_review_decisions() {
+        assert!(LicenseReviewDecision::Verify.applies_to("pending"));
+        assert!(!LicenseReviewDecision::Verify.applies_to("verified"));
+        assert!(LicenseReviewDecision::Revoke.applies_to("verified"));
+        assert!(!LicenseReviewDecision::Revoke.applies_to("pending"));
+        assert_eq!(LicenseReviewDecision::Reject.resulting_status(), LicenseStatus::Rejected);
+    }
+
+    #[test]
+    fn test_document_content_type() {
+        assert_eq!(document_content_type(b"%PDF-1.7\n..."), Some("application/pdf"));
+        assert_eq!(document_content_type(&[0xFF, 0xD8, 0xFF, 0xE0]), Some("image/jpeg"));
+        assert_eq!(document_content_type(b"PK\x03\x04"), None);
+    }
+}
@@ -0,0 +1,87 @@
+/// Maintenance mode: status, drain progress and the superadmin switch
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Background jobs of one type still running
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct RunningJobCount {
+    pub job_type: String,
+    pub running: i64,
+}
+
+/// Work still running on any instance. Nothing new starts while in maintenance, so
+/// these only go down; once all are zero the platform is drained.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct DrainStatus {
+    pub running_background_jobs: i64,
+    pub running_jobs_by_type: Vec<RunningJobCount>,
+    /// Alert scheduler jobs holding a lease
+    pub running_alert_jobs: i64,
+    /// Scheduled ERP syncs in progress
+    pub running_erp_syncs: i64,
+    /// Whether maintenance is on and nothing is running
+    pub drained: bool,
+}
+
+impl DrainStatus {
+    pub fn new(
+        maintenance_enabled: bool,
+        running_jobs_by_type: Vec<RunningJobCount>,
+        running_alert_jobs: i64,
+        running_erp_syncs: i64,
+    ) -> Self {
+        let running_background_jobs = running_jobs_by_type.iter().map(|jobs| jobs.running).sum();
+        Self {
+            drained: maintenance_enabled
+                && running_background_jobs == 0
+                && running_alert_jobs == 0
+                && running_erp_syncs == 0,
+            running_background_jobs,
+            running_jobs_by_type,
+            running_alert_jobs,
+            running_erp_syncs,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct MaintenanceStatus {
+    pub enabled: bool,
+    /// When maintenance was last switched through the API; None when the
+    /// state comes from the environment
+    pub changed_at: Option<DateTime<Utc>>,
+    pub changed_by: Option<Uuid>,
+    pub retry_after_seconds: u64,
+    pub message: String,
+    pub drain: DrainStatus,
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct UpdateMaintenanceRequest {
+    pub enabled: bool,
+    /// Retry-After for rejected requests; unchanged when omitted
+    pub retry_after_seconds: Option<i64>,
+    /// Message for rejected requests; unchanged when omitted
+    pub message: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drained_only_in_maintenance_with_nothing_running() {
+        let jobs = |running| vec![RunningJobCount { job_type: "erp_sync".to_string(), running }];
+
+        assert!(DrainStatus::new(true, jobs(0), 0, 0).drained);
+        assert!(!DrainStatus::new(false, jobs(0), 0, 0).drained);
+        assert!(!DrainStatus::new(true, jobs(2), 0, 0).drained);
+        assert!(!DrainStatus::new(true, vec![], 1, 0).drained);
+        assert!(!DrainStatus::new(true, vec![], 0, 1).drained);
+        assert_eq!(DrainStatus::new(true, jobs(3), 0, 0).running_background_jobs, 3);
+    }
+}
@@ -1,5 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use sqlx::FromRow;
 use uuid::Uuid;
 use validator::{Validate, ValidationError};
@@ -25,7 +26,7 @@ pub struct Inquiry {
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Deserialize, Validate)]
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, ToSchema)]
 pub struct CreateInquiryRequest {
     pub inventory_id: Uuid,
     #[validate(range(min = 1, message = "Quantity must be at least 1"))]
@@ -34,14 +35,14 @@ pub struct CreateInquiryRequest {
     pub message: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct UpdateInquiryRequest {
     pub status: Option<String>,
     #[validate(length(max = 1000, message = "Response message too long"))]
     pub response_message: Option<String>,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, ToSchema)]
 pub struct InquiryResponse {
     pub id: Uuid,
     pub inventory_id: Uuid,
@@ -59,6 +60,9 @@ pub struct InquiryResponse {
     pub buyer: Option<crate::models::user::UserResponse>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub seller: Option<crate::models::user::UserResponse>,
+    /// Regulatory requirements of the trade; set when the inquiry is created
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub regulatory_requirements: Option<Vec<crate::models::regulatory_rule::RequirementResult>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -72,9 +76,12 @@ pub struct Transaction {
     pub total_price: rust_decimal::Decimal,
     pub transaction_date: DateTime<Utc>,
     pub status: String,
+    /// ERP document numbers of the orders exported for the transaction
+    pub erp_sales_order_number: Option<String>,
+    pub erp_purchase_order_number: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct CreateTransactionRequest {
     pub inquiry_id: Uuid,
     #[validate(range(min = 1, message = "Quantity must be at least 1"))]
@@ -83,7 +90,7 @@ pub struct CreateTransactionRequest {
     pub unit_price: rust_decimal::Decimal,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, ToSchema)]
 pub struct TransactionResponse {
     pub id: Uuid,
     pub inquiry_id: Uuid,
@@ -94,6 +101,12 @@ pub struct TransactionResponse {
     pub total_price: rust_decimal::Decimal,
     pub transaction_date: DateTime<Utc>,
     pub status: String,
+    /// ERP document numbers of the orders exported for the transaction
+    pub erp_sales_order_number: Option<String>,
+    pub erp_purchase_order_number: Option<String>,
+    /// Regulatory requirements of the trade; set when the transaction is created
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub regulatory_requirements: Option<Vec<crate::models::regulatory_rule::RequirementResult>>,
 }
 
 impl From<Inquiry> for InquiryResponse {
@@ -110,6 +123,7 @@ impl From<Inquiry> for InquiryResponse {
             inventory: None,
             buyer: None,
             seller: None,
+            regulatory_requirements: None,
         }
     }
 }
@@ -126,6 +140,9 @@ impl From<Transaction> for TransactionResponse {
             total_price: transaction.total_price,
             transaction_date: transaction.transaction_date,
             status: transaction.status,
+            erp_sales_order_number: transaction.erp_sales_order_number,
+            erp_purchase_order_number: transaction.erp_purchase_order_number,
+            regulatory_requirements: None,
         }
     }
 }
\ No newline at end of file
@@ -9,6 +9,57 @@ pub mod ai_import;
 pub mod nl_query;
 pub mod inquiry_assistant;
 pub mod alerts;
+pub mod product_image;
+pub mod catalog_subscription;
+pub mod ingredient;
+pub mod openfda_device;
+pub mod openfda_recall;
+pub mod regulator_catalog;
+pub mod sync_preview;
+pub mod sync_anomaly;
+pub mod sync_source;
+pub mod catalog_export;
+pub mod federated_search;
+pub mod sync_dashboard;
+pub mod edi;
+pub mod accounting;
+pub mod semantic_search;
+pub mod pricing_suggestion;
+pub mod ai_quota;
+pub mod knowledge_base;
+pub mod anomaly;
+pub mod webhook;
+pub mod alert_channel;
+pub mod realtime;
+pub mod announcement;
+pub mod dscsa;
+pub mod product_verification;
+pub mod electronic_signature;
+pub mod data_retention;
+pub mod legal_hold;
+pub mod document_version;
+pub mod temperature_excursion;
+pub mod license;
+pub mod sanctions;
+pub mod regulatory_rule;
+pub mod audit_chain;
+pub mod document_batch;
+pub mod health;
+pub mod background_job;
+pub mod ops_dashboard;
+pub mod billing;
+pub mod user_suspension;
+pub mod settings;
+pub mod admin_analytics;
+pub mod maintenance;
+pub mod payload_capture;
+pub mod verification;
+pub mod search_engine;
+pub mod data_export;
+pub mod summary_aggregate;
+pub mod fhir;
+pub mod rate_limit;
+pub mod batch_operation;
 
 pub use user::*;
 pub use pharmaceutical::*;
@@ -20,4 +71,55 @@ pub use inquiry_message::*;
 pub use ai_import::*;
 pub use nl_query::*;
 pub use inquiry_assistant::*;
-pub use alerts::*;
\ No newline at end of file
+pub use alerts::*;
+pub use product_image::*;
+pub use catalog_subscription::*;
+pub use ingredient::*;
+pub use openfda_device::*;
+pub use openfda_recall::*;
+pub use regulator_catalog::*;
+pub use sync_preview::*;
+pub use sync_anomaly::*;
+pub use sync_source::*;
+pub use catalog_export::*;
+pub use federated_search::*;
+pub use sync_dashboard::*;
+pub use edi::*;
+pub use accounting::*;
+pub use semantic_search::*;
+pub use pricing_suggestion::*;
+pub use ai_quota::*;
+pub use knowledge_base::*;
+pub use anomaly::*;
+pub use webhook::*;
+pub use alert_channel::*;
+pub use realtime::*;
+pub use announcement::*;
+pub use dscsa::*;
+pub use product_verification::*;
+pub use electronic_signature::*;
+pub use data_retention::*;
+pub use legal_hold::*;
+pub use document_version::*;
+pub use temperature_excursion::*;
+pub use license::*;
+pub use sanctions::*;
+pub use regulatory_rule::*;
+pub use audit_chain::*;
+pub use document_batch::*;
+pub use health::*;
+pub use background_job::*;
+pub use ops_dashboard::*;
+pub use billing::*;
+pub use user_suspension::*;
+pub use settings::*;
+pub use admin_analytics::*;
+pub use maintenance::*;
+pub use payload_capture::*;
+pub use verification::*;
+pub use search_engine::*;
+pub use data_export::*;
+pub use summary_aggregate::*;
+pub use fhir::*;
+pub use rate_limit::*;
+pub use batch_operation::*;
\ No newline at end of file
@@ -1,6 +1,7 @@
 /// Natural Language Query models for AI-powered database querying
 
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 use sqlx::FromRow;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
@@ -40,12 +41,12 @@ pub struct NlQueryFavorite {
 // API Request/Response Models
 // ============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct ExecuteQueryRequest {
     pub query: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct QueryResponse {
     pub id: Uuid,
     pub status: String,
@@ -105,7 +106,7 @@ pub struct QueryHistoryResponse {
     pub queries_remaining: i32,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct QueryHistoryItem {
     pub id: Uuid,
     pub query_text: String,
@@ -115,14 +116,14 @@ pub struct QueryHistoryItem {
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct SaveFavoriteRequest {
     pub query_text: String,
     pub description: Option<String>,
     pub category: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct FavoriteResponse {
     pub id: Uuid,
     pub query_text: String,
@@ -172,3 +173,265 @@ pub enum AiAssistantResponse {
         parameters: Option<Vec<serde_json::Value>>,
     },
 }
+
+// ============================================================================
+// Report Exports and Schedules
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    Csv,
+    Xlsx,
+}
+
+impl ReportFormat {
+    /// `None` means CSV
+    pub fn parse(format: Option<&str>) -> Result<Self, String> {
+        match format.map(|f| f.trim().to_lowercase()).as_deref() {
+            None | Some("") | Some("csv") => Ok(ReportFormat::Csv),
+            Some("xlsx") | Some("excel") => Ok(ReportFormat::Xlsx),
+            Some(other) => Err(format!("Unsupported report format '{}'. Use csv or xlsx", other)),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReportFormat::Csv => "csv",
+            ReportFormat::Xlsx => "xlsx",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ReportFormat::Csv => "text/csv; charset=utf-8",
+            ReportFormat::Xlsx => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFrequency {
+    Daily,
+    Weekly,
+}
+
+impl ReportFrequency {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "daily" => Some(ReportFrequency::Daily),
+            "weekly" => Some(ReportFrequency::Weekly),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReportFrequency::Daily => "daily",
+            ReportFrequency::Weekly => "weekly",
+        }
+    }
+
+    /// Most runs a schedule can make in a calendar month (for quota projections)
+    pub fn max_monthly_runs(&self) -> i32 {
+        match self {
+            ReportFrequency::Daily => 31,
+            ReportFrequency::Weekly => 5,
+        }
+    }
+}
+
+/// Next run strictly after `after`: the top of `hour_utc`, on `day_of_week`
+/// (0 = Monday) for weekly schedules
+pub fn next_report_run(
+    after: DateTime<Utc>,
+    frequency: ReportFrequency,
+    day_of_week: Option<i16>,
+    hour_utc: i16,
+) -> DateTime<Utc> {
+    use chrono::{Datelike, Duration, TimeZone};
+
+    let hour = hour_utc.clamp(0, 23) as u32;
+    let mut date = after.date_naive();
+
+    loop {
+        let candidate = Utc.from_utc_datetime(&date.and_hms_opt(hour, 0, 0).expect("valid hour"));
+        let day_matches = match frequency {
+            ReportFrequency::Daily => true,
+            ReportFrequency::Weekly => {
+                date.weekday().num_days_from_monday() as i16 == day_of_week.unwrap_or(0).clamp(0, 6)
+            }
+        };
+
+        if day_matches && candidate > after {
+            return candidate;
+        }
+        date += Duration::days(1);
+    }
+}
+
+/// Column headers and rows of stored query results. Columns are listed in the order
+/// they first appear, so rows missing a column still line up.
+pub fn result_table(results: &[serde_json::Value]) -> (Vec<String>, Vec<Vec<serde_json::Value>>) {
+    let mut headers: Vec<String> = Vec::new();
+    for row in results {
+        if let serde_json::Value::Object(map) = row {
+            for key in map.keys() {
+                if !headers.contains(key) {
+                    headers.push(key.clone());
+                }
+            }
+        }
+    }
+
+    let rows = results
+        .iter()
+        .map(|row| {
+            headers
+                .iter()
+                .map(|h| row.get(h).cloned().unwrap_or(serde_json::Value::Null))
+                .collect()
+        })
+        .collect();
+
+    (headers, rows)
+}
+
+/// Text of a result cell as written to CSV (null is empty)
+pub fn cell_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExportQueryParams {
+    /// csv (default) or xlsx
+    pub format: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct NlReportSchedule {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub favorite_id: Uuid,
+    pub name: String,
+    pub frequency: String,
+    pub day_of_week: Option<i16>,
+    pub hour_utc: i16,
+    pub format: String,
+    pub email_delivery: bool,
+    pub is_active: bool,
+    pub next_run_at: DateTime<Utc>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub last_status: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct NlReportFile {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub schedule_id: Option<Uuid>,
+    pub session_id: Option<Uuid>,
+    pub status: String,
+    pub format: String,
+    pub filename: Option<String>,
+    #[serde(skip_serializing)]
+    pub file_path: Option<String>,
+    pub file_size_bytes: Option<i64>,
+    pub row_count: Option<i32>,
+    pub emailed: bool,
+    pub error_message: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateReportScheduleRequest {
+    pub favorite_id: Uuid,
+    /// Defaults to the favorite's description or query text
+    pub name: Option<String>,
+    pub frequency: ReportFrequency,
+    /// 0 = Monday .. 6 = Sunday (weekly only, default Monday)
+    pub day_of_week: Option<i16>,
+    /// Hour of day in UTC (default 6)
+    pub hour_utc: Option<i16>,
+    pub format: Option<String>,
+    #[serde(default)]
+    pub email_delivery: bool,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateReportScheduleRequest {
+    pub name: Option<String>,
+    pub frequency: Option<ReportFrequency>,
+    pub day_of_week: Option<i16>,
+    pub hour_utc: Option<i16>,
+    pub format: Option<String>,
+    pub email_delivery: Option<bool>,
+    pub is_active: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ReportFilesQuery {
+    pub schedule_id: Option<Uuid>,
+    pub limit: Option<i64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_next_report_run() {
+        // Wednesday 2026-03-04 10:30 UTC
+        let now = Utc.with_ymd_and_hms(2026, 3, 4, 10, 30, 0).unwrap();
+
+        assert_eq!(
+            next_report_run(now, ReportFrequency::Daily, None, 12),
+            Utc.with_ymd_and_hms(2026, 3, 4, 12, 0, 0).unwrap()
+        );
+        assert_eq!(
+            next_report_run(now, ReportFrequency::Daily, None, 6),
+            Utc.with_ymd_and_hms(2026, 3, 5, 6, 0, 0).unwrap()
+        );
+        // Monday
+        assert_eq!(
+            next_report_run(now, ReportFrequency::Weekly, Some(0), 6),
+            Utc.with_ymd_and_hms(2026, 3, 9, 6, 0, 0).unwrap()
+        );
+        // Same weekday, hour already passed: a week later
+        assert_eq!(
+            next_report_run(now, ReportFrequency::Weekly, Some(2), 9),
+            Utc.with_ymd_and_hms(2026, 3, 11, 9, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_result_table() {
+        let results = vec![
+            serde_json::json!({"ndc": "12345-678-90", "quantity": 10}),
+            serde_json::json!({"ndc": "98765-432-10", "expiry": "2027-01-31"}),
+        ];
+        let (headers, rows) = result_table(&results);
+
+        assert_eq!(headers, vec!["ndc", "quantity", "expiry"]);
+        assert_eq!(rows[1][1], serde_json::Value::Null);
+        assert_eq!(cell_text(&rows[0][1]), "10");
+        assert_eq!(cell_text(&rows[1][1]), "");
+    }
+
+    #[test]
+    fn test_report_format() {
+        assert_eq!(ReportFormat::parse(None).unwrap(), ReportFormat::Csv);
+        assert_eq!(ReportFormat::parse(Some("XLSX")).unwrap(), ReportFormat::Xlsx);
+        assert!(ReportFormat::parse(Some("pdf")).is_err());
+    }
+}
@@ -1,7 +1,9 @@
 use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 use sqlx::FromRow;
 use uuid::Uuid;
+use crate::utils::BloomFilter;
 
 // ============================================================================
 // OpenFDA API Response Models
@@ -78,7 +80,7 @@ pub struct OpenFdaData {
 // Database Models
 // ============================================================================
 
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct OpenFdaCatalogEntry {
     pub id: Uuid,
     pub product_ndc: String,
@@ -105,7 +107,7 @@ pub struct OpenFdaCatalogEntry {
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, ToSchema)]
 pub struct OpenFdaCatalogResponse {
     pub id: Uuid,
     pub product_ndc: String,
@@ -119,9 +121,12 @@ pub struct OpenFdaCatalogResponse {
     pub dea_schedule: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct OpenFdaSearchRequest {
     pub query: Option<String>,
+    /// Comma-separated fields to count values of, e.g. `dosage_form,route`
+    pub facets: Option<String>,
     pub limit: Option<i64>,
     pub offset: Option<i64>,
 }
@@ -149,10 +154,74 @@ pub struct OpenFdaSyncLog {
     pub sync_type: Option<String>,
     pub cancelled_at: Option<DateTime<Utc>>,
     pub cancelled_by: Option<Uuid>,
+    pub last_heartbeat_at: Option<DateTime<Utc>>,
+    pub resume_count: i32,
+    /// Set when guardrails marked the run `suspect`
+    pub anomalies: Option<serde_json::Value>,
+}
+
+/// Delta sync checkpoint stored in `openfda_sync_log.checkpoint` on completion
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct DeltaCheckpoint {
+    /// OpenFDA `meta.last_updated` of the dataset that was synced
+    pub dataset_last_updated: Option<String>,
+    /// Record count per search partition (e.g. `brand_name:a*`) at sync time
+    pub partition_totals: std::collections::HashMap<String, i64>,
+    pub synced_on: NaiveDate,
+    /// Date of the last full sync; delta runs carry it forward unchanged (None on
+    /// checkpoints written before it was recorded)
+    #[serde(default)]
+    pub full_synced_on: Option<NaiveDate>,
+}
+
+impl DeltaCheckpoint {
+    /// A delta run can't see records edited in place (partition counts don't change),
+    /// so a full sync is due once the last one is older than `max_age_days`
+    pub fn needs_full_sync(&self, today: NaiveDate, max_age_days: i64) -> bool {
+        match self.full_synced_on {
+            Some(full_synced_on) => (today - full_synced_on).num_days() > max_age_days,
+            None => true,
+        }
+    }
+}
+
+/// Position of an unfinished partitioned sync, stored in `openfda_sync_log.resume_state`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncResumeState {
+    /// Search partitions of the run, in order
+    pub partitions: Vec<PartitionCheckpoint>,
+    /// NDCs upserted so far; positives are confirmed against the catalog on resume
+    pub seen_ndcs: BloomFilter,
+    pub total_expected: i64,
+    pub dataset_last_updated: Option<String>,
+    pub partition_totals: std::collections::HashMap<String, i64>,
+    pub totals: SyncTotals,
+}
+
+/// Progress of one search partition (partitions are fetched concurrently)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartitionCheckpoint {
+    /// Search filter, e.g. `brand_name:a*` (`None` = unfiltered)
+    pub filter: Option<String>,
+    /// Skip offset of the next page to fetch
+    pub skip: usize,
+    pub done: bool,
+}
+
+/// Running record counters of a sync
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
+pub struct SyncTotals {
+    pub fetched: i32,
+    pub inserted: i32,
+    pub updated: i32,
+    pub skipped: i32,
+    pub failed: i32,
+    pub batches: i32,
+    pub api_time_ms: i32,
 }
 
 /// Response for sync progress queries
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, ToSchema)]
 pub struct SyncProgressResponse {
     pub id: Uuid,
     pub status: String,
@@ -171,6 +240,22 @@ pub struct SyncProgressResponse {
     pub error_message: Option<String>,
     pub started_at: DateTime<Utc>,
     pub completed_at: Option<DateTime<Utc>>,
+    pub resume_count: i32,
+    /// Failed runs, runs stopped by a shutdown and in_progress runs whose heartbeat
+    /// stopped can be resumed
+    pub interrupted: bool,
+}
+
+impl OpenFdaSyncLog {
+    /// In-progress syncs are considered interrupted after this long without a heartbeat
+    pub const STALE_AFTER_MINUTES: i64 = 10;
+
+    /// True when the sync is still marked in_progress but its worker stopped reporting
+    pub fn is_stale(&self) -> bool {
+        let last_seen = self.last_heartbeat_at.unwrap_or(self.sync_started_at);
+        self.status == "in_progress"
+            && Utc::now() - last_seen > chrono::Duration::minutes(Self::STALE_AFTER_MINUTES)
+    }
 }
 
 impl From<OpenFdaSyncLog> for SyncProgressResponse {
@@ -209,10 +294,86 @@ impl From<OpenFdaSyncLog> for SyncProgressResponse {
             error_message: log.error_message,
             started_at: log.sync_started_at,
             completed_at: log.sync_completed_at,
+            resume_count: log.resume_count,
+            interrupted: log.status == "failed" || log.status == "interrupted" || log.is_stale(),
         }
     }
 }
 
+// ============================================================================
+// Catalog History
+// ============================================================================
+
+/// A version of an NDC directory entry, valid from `valid_from` until `valid_to`
+/// (`None` while it is the version in force)
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct OpenFdaCatalogVersion {
+    pub id: Uuid,
+    pub product_ndc: String,
+    pub version: i32,
+    pub data: serde_json::Value,
+    pub valid_from: DateTime<Utc>,
+    pub valid_to: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CatalogVersionResponse {
+    #[serde(flatten)]
+    pub version: OpenFdaCatalogVersion,
+    /// Fields that differ from the previous version (empty for the first version)
+    pub changed_fields: Vec<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CatalogHistoryResponse {
+    pub product_ndc: String,
+    pub current_version: Option<i32>,
+    /// Newest first
+    pub versions: Vec<CatalogVersionResponse>,
+}
+
+impl CatalogHistoryResponse {
+    /// Build from versions ordered newest first
+    pub fn from_versions(product_ndc: &str, versions: Vec<OpenFdaCatalogVersion>) -> Self {
+        let changes: Vec<Vec<String>> = versions
+            .iter()
+            .enumerate()
+            .map(|(i, version)| match versions.get(i + 1) {
+                Some(previous) => changed_fields(&previous.data, &version.data),
+                None => Vec::new(),
+            })
+            .collect();
+
+        Self {
+            product_ndc: product_ndc.to_string(),
+            current_version: versions.iter().find(|v| v.valid_to.is_none()).map(|v| v.version),
+            versions: versions
+                .into_iter()
+                .zip(changes)
+                .map(|(version, changed_fields)| CatalogVersionResponse { version, changed_fields })
+                .collect(),
+        }
+    }
+}
+
+/// Top-level keys whose values differ between two version snapshots, sorted
+pub fn changed_fields(previous: &serde_json::Value, current: &serde_json::Value) -> Vec<String> {
+    let empty = serde_json::Map::new();
+    let previous = previous.as_object().unwrap_or(&empty);
+    let current = current.as_object().unwrap_or(&empty);
+
+    let mut fields: Vec<String> = previous
+        .keys()
+        .chain(current.keys())
+        .filter(|key| previous.get(*key) != current.get(*key))
+        .cloned()
+        .collect();
+    fields.sort();
+    fields.dedup();
+    fields
+}
+
 // ============================================================================
 // Conversion Implementations
 // ============================================================================
@@ -302,3 +463,67 @@ impl From<OpenFdaCatalogEntry> for OpenFdaCatalogResponse {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn version(number: i32, data: serde_json::Value, current: bool) -> OpenFdaCatalogVersion {
+        OpenFdaCatalogVersion {
+            id: Uuid::new_v4(),
+            product_ndc: "0002-1433".to_string(),
+            version: number,
+            data,
+            valid_from: Utc::now(),
+            valid_to: if current { None } else { Some(Utc::now()) },
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_changed_fields() {
+        let previous = json!({ "brand_name": "Trulicity", "strength": "0.75 mg/0.5mL", "dea_schedule": "CII" });
+        let current = json!({ "brand_name": "Trulicity", "strength": "1.5 mg/0.5mL", "finished": true });
+
+        assert_eq!(changed_fields(&previous, &current), vec!["dea_schedule", "finished", "strength"]);
+        assert!(changed_fields(&current, &current).is_empty());
+    }
+
+    #[test]
+    fn test_history_response() {
+        let history = CatalogHistoryResponse::from_versions("0002-1433", vec![
+            version(2, json!({ "labeler_name": "Eli Lilly and Company" }), true),
+            version(1, json!({ "labeler_name": "Eli Lilly" }), false),
+        ]);
+
+        assert_eq!(history.current_version, Some(2));
+        assert_eq!(history.versions[0].changed_fields, vec!["labeler_name"]);
+        assert!(history.versions[1].changed_fields.is_empty());
+    }
+
+    #[test]
+    fn test_stale_full_sync_forces_full_run() {
+        let today = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        let mut checkpoint = DeltaCheckpoint {
+            // A delta run completed today...
+            synced_on: today,
+            // ...but the last full run is older than the limit
+            full_synced_on: NaiveDate::from_ymd_opt(2026, 9, 1),
+            ..Default::default()
+        };
+        assert!(checkpoint.needs_full_sync(today, 30));
+
+        checkpoint.full_synced_on = NaiveDate::from_ymd_opt(2026, 10, 1);
+        assert!(!checkpoint.needs_full_sync(today, 30));
+
+        // Checkpoints from before the full sync date was recorded
+        let legacy: DeltaCheckpoint = serde_json::from_value(json!({
+            "dataset_last_updated": "2026-10-15",
+            "partition_totals": {},
+            "synced_on": "2026-10-16",
+        }))
+        .unwrap();
+        assert!(legacy.needs_full_sync(today, 30));
+    }
+}
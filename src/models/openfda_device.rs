@@ -0,0 +1,253 @@
+/// OpenFDA device UDI (GUDID) models
+///
+/// Source: https://api.fda.gov/device/udi.json
+/// Device records are stored separately from the NDC drug catalog so device listings can
+/// reference a GUDID primary device identifier instead of reusing drug fields.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use sqlx::FromRow;
+use uuid::Uuid;
+use crate::models::openfda::OpenFdaMeta;
+
+// ============================================================================
+// API Response Models
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct OpenFdaDeviceApiResponse {
+    pub meta: OpenFdaMeta,
+    pub results: Vec<OpenFdaUdiRecord>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct OpenFdaUdiRecord {
+    pub brand_name: Option<String>,
+    pub version_or_model_number: Option<String>,
+    pub catalog_number: Option<String>,
+    pub company_name: Option<String>,
+    pub device_description: Option<String>,
+    pub identifiers: Option<Vec<UdiIdentifier>>,
+    pub product_codes: Option<Vec<UdiProductCode>>,
+    pub gmdn_terms: Option<Vec<serde_json::Value>>,
+    // GUDID booleans arrive as "true"/"false" strings
+    pub is_rx: Option<serde_json::Value>,
+    pub is_otc: Option<serde_json::Value>,
+    pub is_single_use: Option<serde_json::Value>,
+    pub sterilization: Option<serde_json::Value>,
+    pub mri_safety: Option<String>,
+    pub commercial_distribution_status: Option<String>,
+    pub record_status: Option<String>,
+    pub publish_date: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct UdiIdentifier {
+    pub id: Option<String>,
+    pub issuing_agency: Option<String>,
+    #[serde(rename = "type")]
+    pub identifier_type: Option<String>,
+    pub unit_of_use_id: Option<String>,
+    pub quantity_per_package: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct UdiProductCode {
+    pub code: Option<String>,
+    pub name: Option<String>,
+    pub openfda: Option<UdiProductCodeOpenFda>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct UdiProductCodeOpenFda {
+    pub device_class: Option<String>,
+    pub device_name: Option<String>,
+}
+
+// ============================================================================
+// Database Models
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct OpenFdaDeviceEntry {
+    pub id: Uuid,
+    pub primary_di: String,
+    pub issuing_agency: Option<String>,
+    pub brand_name: Option<String>,
+    pub version_model_number: Option<String>,
+    pub catalog_number: Option<String>,
+    pub company_name: Option<String>,
+    pub device_description: Option<String>,
+    pub device_class: Option<String>,
+    pub product_codes: Option<Vec<String>>,
+    pub gmdn_terms: Option<serde_json::Value>,
+    pub is_rx: Option<bool>,
+    pub is_otc: Option<bool>,
+    pub is_single_use: Option<bool>,
+    pub is_sterile: Option<bool>,
+    pub mri_safety: Option<String>,
+    pub commercial_distribution_status: Option<String>,
+    pub record_status: Option<String>,
+    pub publish_date: Option<NaiveDate>,
+    pub identifiers: Option<serde_json::Value>,
+    pub last_synced_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DeviceSearchRequest {
+    pub query: Option<String>,
+    pub company_name: Option<String>,
+    pub device_class: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+// ============================================================================
+// Conversion
+// ============================================================================
+
+impl OpenFdaUdiRecord {
+    /// The primary device identifier, if the record has one
+    pub fn primary_identifier(&self) -> Option<&UdiIdentifier> {
+        self.identifiers.as_ref()?.iter().find(|i| {
+            i.identifier_type.as_deref().map(|t| t.eq_ignore_ascii_case("primary")).unwrap_or(false)
+                && i.id.as_deref().map(|id| !id.trim().is_empty()).unwrap_or(false)
+        })
+    }
+
+    /// Records without a primary DI cannot be referenced by listings and are skipped
+    pub fn to_device_entry(&self) -> Option<OpenFdaDeviceEntry> {
+        let primary = self.primary_identifier()?;
+        let now = Utc::now();
+
+        let product_codes: Vec<String> = self
+            .product_codes
+            .iter()
+            .flatten()
+            .filter_map(|p| p.code.clone())
+            .collect();
+
+        // Highest numeric class across product codes (class 3 is the most regulated);
+        // U/N/F (unclassified, not classified, HDE) only when no numeric class exists
+        let classes: Vec<String> = self
+            .product_codes
+            .iter()
+            .flatten()
+            .filter_map(|p| p.openfda.as_ref()?.device_class.clone())
+            .collect();
+        let device_class = classes
+            .iter()
+            .filter(|c| matches!(c.as_str(), "1" | "2" | "3"))
+            .max()
+            .or_else(|| classes.first())
+            .cloned();
+
+        Some(OpenFdaDeviceEntry {
+            id: Uuid::new_v4(),
+            primary_di: primary.id.clone()?.trim().to_string(),
+            issuing_agency: primary.issuing_agency.clone(),
+            brand_name: self.brand_name.clone(),
+            version_model_number: self.version_or_model_number.clone(),
+            catalog_number: self.catalog_number.clone(),
+            company_name: self.company_name.clone(),
+            device_description: self.device_description.clone(),
+            device_class,
+            product_codes: if product_codes.is_empty() { None } else { Some(product_codes) },
+            gmdn_terms: self.gmdn_terms.as_ref().map(|t| serde_json::Value::Array(t.clone())),
+            is_rx: parse_flag(self.is_rx.as_ref()),
+            is_otc: parse_flag(self.is_otc.as_ref()),
+            is_single_use: parse_flag(self.is_single_use.as_ref()),
+            is_sterile: parse_flag(self.sterilization.as_ref().and_then(|s| s.get("is_sterile"))),
+            mri_safety: self.mri_safety.clone(),
+            commercial_distribution_status: self.commercial_distribution_status.clone(),
+            record_status: self.record_status.clone(),
+            publish_date: self.publish_date.as_deref().and_then(parse_udi_date),
+            identifiers: self.identifiers.as_ref().and_then(|i| serde_json::to_value(i).ok()),
+            last_synced_at: now,
+            created_at: now,
+            updated_at: now,
+        })
+    }
+}
+
+fn parse_flag(value: Option<&serde_json::Value>) -> Option<bool> {
+    match value? {
+        serde_json::Value::Bool(b) => Some(*b),
+        serde_json::Value::String(s) => match s.to_lowercase().as_str() {
+            "true" => Some(true),
+            "false" => Some(false),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// GUDID dates are YYYY-MM-DD; some older records use YYYYMMDD
+fn parse_udi_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(value, "%Y%m%d"))
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn record(value: serde_json::Value) -> OpenFdaUdiRecord {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_to_device_entry() {
+        let entry = record(json!({
+            "brand_name": "SureFlow",
+            "company_name": "Acme Medical",
+            "identifiers": [
+                { "id": "10884521062856", "issuing_agency": "GS1", "type": "Package" },
+                { "id": " 00884521062859 ", "issuing_agency": "GS1", "type": "Primary" },
+            ],
+            "product_codes": [
+                { "code": "FMF", "openfda": { "device_class": "2" } },
+                { "code": "LZA", "openfda": { "device_class": "U" } },
+            ],
+            "is_rx": "true",
+            "is_otc": "false",
+            "sterilization": { "is_sterile": true },
+            "publish_date": "20180315",
+        }))
+        .to_device_entry()
+        .unwrap();
+
+        assert_eq!(entry.primary_di, "00884521062859");
+        assert_eq!(entry.issuing_agency.as_deref(), Some("GS1"));
+        assert_eq!(entry.product_codes, Some(vec!["FMF".to_string(), "LZA".to_string()]));
+        assert_eq!(entry.device_class.as_deref(), Some("2"));
+        assert_eq!((entry.is_rx, entry.is_otc, entry.is_sterile), (Some(true), Some(false), Some(true)));
+        assert_eq!(entry.is_single_use, None);
+        assert_eq!(entry.publish_date, NaiveDate::from_ymd_opt(2018, 3, 15));
+    }
+
+    #[test]
+    fn test_to_device_entry_without_numeric_class_or_primary_di() {
+        let unclassified = record(json!({
+            "identifiers": [{ "id": "00884521062859", "type": "Primary" }],
+            "product_codes": [{ "code": "LZA", "openfda": { "device_class": "U" } }],
+        }))
+        .to_device_entry()
+        .unwrap();
+        assert_eq!(unclassified.device_class.as_deref(), Some("U"));
+        assert_eq!(unclassified.product_codes, Some(vec!["LZA".to_string()]));
+
+        // Records without a primary DI can't be referenced and are skipped
+        assert!(record(json!({ "identifiers": [{ "id": "10884521062856", "type": "Package" }] }))
+            .to_device_entry()
+            .is_none());
+        assert!(record(json!({ "identifiers": [{ "id": " ", "type": "Primary" }] })).to_device_entry().is_none());
+        assert!(record(json!({})).to_device_entry().is_none());
+    }
+}
@@ -0,0 +1,296 @@
+/// OpenFDA drug enforcement (recall) models
+///
+/// Source: https://api.fda.gov/drug/enforcement.json
+/// Enforcement reports often carry an empty `openfda` section, so NDCs quoted in the
+/// product description and code info are extracted as well to link recalls to the catalog.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use sqlx::FromRow;
+use uuid::Uuid;
+use crate::models::openfda::{OpenFdaCatalogEntry, OpenFdaMeta};
+
+// ============================================================================
+// API Response Models
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct OpenFdaEnforcementApiResponse {
+    pub meta: OpenFdaMeta,
+    pub results: Vec<OpenFdaEnforcementRecord>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct OpenFdaEnforcementRecord {
+    pub recall_number: Option<String>,
+    pub event_id: Option<String>,
+    pub status: Option<String>,
+    pub classification: Option<String>,
+    pub product_type: Option<String>,
+    pub product_description: Option<String>,
+    pub reason_for_recall: Option<String>,
+    pub code_info: Option<String>,
+    pub product_quantity: Option<String>,
+    pub distribution_pattern: Option<String>,
+    pub recalling_firm: Option<String>,
+    pub city: Option<String>,
+    pub state: Option<String>,
+    pub country: Option<String>,
+    pub voluntary_mandated: Option<String>,
+    pub initial_firm_notification: Option<String>,
+    pub recall_initiation_date: Option<String>,
+    pub report_date: Option<String>,
+    pub center_classification_date: Option<String>,
+    pub termination_date: Option<String>,
+    pub openfda: Option<serde_json::Value>,
+}
+
+// ============================================================================
+// Database Models
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct OpenFdaRecall {
+    pub id: Uuid,
+    pub recall_number: String,
+    pub event_id: Option<String>,
+    pub status: Option<String>,
+    pub classification: Option<String>,
+    pub product_type: Option<String>,
+    pub product_description: Option<String>,
+    pub reason_for_recall: Option<String>,
+    pub code_info: Option<String>,
+    pub product_quantity: Option<String>,
+    pub distribution_pattern: Option<String>,
+    pub recalling_firm: Option<String>,
+    pub city: Option<String>,
+    pub state: Option<String>,
+    pub country: Option<String>,
+    pub voluntary_mandated: Option<String>,
+    pub initial_firm_notification: Option<String>,
+    pub recall_initiation_date: Option<NaiveDate>,
+    pub report_date: Option<NaiveDate>,
+    pub center_classification_date: Option<NaiveDate>,
+    pub termination_date: Option<NaiveDate>,
+    pub product_ndcs: Vec<String>,
+    pub package_ndcs: Vec<String>,
+    pub openfda_data: Option<serde_json::Value>,
+    pub last_synced_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RecallSearchRequest {
+    /// Matches product description, reason for recall and recall number
+    pub query: Option<String>,
+    /// Product or package NDC
+    pub ndc: Option<String>,
+    pub recalling_firm: Option<String>,
+    /// "Class I", "II", "3", ...
+    pub classification: Option<String>,
+    pub status: Option<String>,
+    pub reported_after: Option<NaiveDate>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// A recall with the catalog entries its NDCs resolve to
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RecallDetailResponse {
+    #[serde(flatten)]
+    pub recall: OpenFdaRecall,
+    pub catalog_matches: Vec<OpenFdaCatalogEntry>,
+}
+
+// ============================================================================
+// Conversion
+// ============================================================================
+
+/// NDCs as printed on labels: 4-4-2, 5-3-2 or 5-4-1 package codes, or 4-4 / 5-3 / 5-4 product codes
+static NDC_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\b(\d{4,5})-(\d{3,4})(?:-(\d{1,2}))?\b").expect("valid NDC regex")
+});
+
+impl OpenFdaEnforcementRecord {
+    /// Records without a recall number cannot be upserted and are skipped
+    pub fn to_recall(&self) -> Option<OpenFdaRecall> {
+        let recall_number = self.recall_number.as_deref()?.trim();
+        if recall_number.is_empty() {
+            return None;
+        }
+
+        let (product_ndcs, package_ndcs) = self.linked_ndcs();
+        let now = Utc::now();
+
+        Some(OpenFdaRecall {
+            id: Uuid::new_v4(),
+            recall_number: recall_number.to_string(),
+            event_id: self.event_id.clone(),
+            status: self.status.clone(),
+            classification: self.classification.clone(),
+            product_type: self.product_type.clone(),
+            product_description: self.product_description.clone(),
+            reason_for_recall: self.reason_for_recall.clone(),
+            code_info: self.code_info.clone(),
+            product_quantity: self.product_quantity.clone(),
+            distribution_pattern: self.distribution_pattern.clone(),
+            recalling_firm: self.recalling_firm.clone(),
+            city: self.city.clone(),
+            state: self.state.clone(),
+            country: self.country.clone(),
+            voluntary_mandated: self.voluntary_mandated.clone(),
+            initial_firm_notification: self.initial_firm_notification.clone(),
+            recall_initiation_date: self.recall_initiation_date.as_deref().and_then(parse_fda_date),
+            report_date: self.report_date.as_deref().and_then(parse_fda_date),
+            center_classification_date: self.center_classification_date.as_deref().and_then(parse_fda_date),
+            termination_date: self.termination_date.as_deref().and_then(parse_fda_date),
+            product_ndcs,
+            package_ndcs,
+            openfda_data: self.openfda.clone().filter(|v| v.as_object().map(|o| !o.is_empty()).unwrap_or(false)),
+            last_synced_at: now,
+            created_at: now,
+            updated_at: now,
+        })
+    }
+
+    /// (product NDCs, package NDCs) from the openfda section and the free-text fields
+    pub fn linked_ndcs(&self) -> (Vec<String>, Vec<String>) {
+        let mut product_ndcs = Vec::new();
+        let mut package_ndcs = Vec::new();
+
+        let openfda_list = |key: &str| -> Vec<String> {
+            self.openfda
+                .as_ref()
+                .and_then(|o| o.get(key))
+                .and_then(|v| v.as_array())
+                .map(|a| a.iter().filter_map(|v| v.as_str().map(str::to_string)).collect())
+                .unwrap_or_default()
+        };
+        product_ndcs.extend(openfda_list("product_ndc"));
+        package_ndcs.extend(openfda_list("package_ndc"));
+
+        let text = [self.product_description.as_deref(), self.code_info.as_deref()];
+        for (product, package) in text.into_iter().flatten().flat_map(extract_ndcs) {
+            product_ndcs.push(product);
+            package_ndcs.extend(package);
+        }
+
+        // Package NDCs imply their product NDC
+        for package in &package_ndcs {
+            if let Some((labeler_product, _)) = package.rsplit_once('-') {
+                product_ndcs.push(labeler_product.to_string());
+            }
+        }
+
+        product_ndcs.sort();
+        product_ndcs.dedup();
+        package_ndcs.sort();
+        package_ndcs.dedup();
+        (product_ndcs, package_ndcs)
+    }
+}
+
+/// Find NDCs in free text. Returns (product NDC, package NDC if the full code was given).
+pub fn extract_ndcs(text: &str) -> Vec<(String, Option<String>)> {
+    NDC_PATTERN
+        .captures_iter(text)
+        .filter(|c| {
+            // 10-digit NDCs only: 4-4-2, 5-3-2 or 5-4-1 (product-only codes: 4-4, 5-3, 5-4)
+            let labeler = c[1].len();
+            let product = c[2].len();
+            match c.get(3).map(|p| p.as_str().len()) {
+                Some(package) => labeler + product + package == 10,
+                None => labeler + product == 8 || labeler + product == 9,
+            }
+        })
+        .map(|c| {
+            let product_ndc = format!("{}-{}", &c[1], &c[2]);
+            let package_ndc = c.get(3).map(|p| format!("{}-{}", product_ndc, p.as_str()));
+            (product_ndc, package_ndc)
+        })
+        .collect()
+}
+
+/// OpenFDA enforcement dates are YYYYMMDD
+fn parse_fda_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.trim(), "%Y%m%d").ok()
+}
+
+/// Normalize a classification filter ("I", "class 2", "Class III") to OpenFDA's "Class I" form
+pub fn normalize_classification(value: &str) -> Option<&'static str> {
+    let lower = value.trim().to_lowercase();
+    match lower.strip_prefix("class").unwrap_or(&lower).trim() {
+        "i" | "1" => Some("Class I"),
+        "ii" | "2" => Some("Class II"),
+        "iii" | "3" => Some("Class III"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_ndcs() {
+        let found = extract_ndcs(
+            "Heparin Sodium Injection, 1,000 USP units/mL, NDC 0409-2720-01 and NDC: 63323-047-10; lot 12345-6",
+        );
+        assert_eq!(
+            found,
+            vec![
+                ("0409-2720".to_string(), Some("0409-2720-01".to_string())),
+                ("63323-047".to_string(), Some("63323-047-10".to_string())),
+            ]
+        );
+
+        // Product-only codes are accepted; dates and phone numbers are not NDCs
+        assert_eq!(extract_ndcs("NDC 50090-1234"), vec![("50090-1234".to_string(), None)]);
+        assert!(extract_ndcs("Exp 2024-05-31, call 1-800-555-0199").is_empty());
+    }
+
+    #[test]
+    fn test_to_recall_links_ndcs() {
+        let record = OpenFdaEnforcementRecord {
+            recall_number: Some("D-0123-2024".to_string()),
+            event_id: None,
+            status: Some("Ongoing".to_string()),
+            classification: Some("Class II".to_string()),
+            product_type: Some("Drugs".to_string()),
+            product_description: Some("Metformin ER 500 mg, 100 tablets, NDC 12345-678-90".to_string()),
+            reason_for_recall: Some("NDMA above acceptable intake".to_string()),
+            code_info: None,
+            product_quantity: None,
+            distribution_pattern: None,
+            recalling_firm: Some("Example Pharma".to_string()),
+            city: None,
+            state: None,
+            country: None,
+            voluntary_mandated: None,
+            initial_firm_notification: None,
+            recall_initiation_date: Some("20240115".to_string()),
+            report_date: Some("20240207".to_string()),
+            center_classification_date: None,
+            termination_date: None,
+            openfda: Some(serde_json::json!({ "product_ndc": ["12345-678", "12345-679"] })),
+        };
+
+        let recall = record.to_recall().unwrap();
+        assert_eq!(recall.product_ndcs, vec!["12345-678", "12345-679"]);
+        assert_eq!(recall.package_ndcs, vec!["12345-678-90"]);
+        assert_eq!(recall.report_date, NaiveDate::from_ymd_opt(2024, 2, 7));
+    }
+
+    #[test]
+    fn test_normalize_classification() {
+        assert_eq!(normalize_classification("I"), Some("Class I"));
+        assert_eq!(normalize_classification("class 2"), Some("Class II"));
+        assert_eq!(normalize_classification("Class III"), Some("Class III"));
+        assert_eq!(normalize_classification("IV"), None);
+    }
+}
@@ -0,0 +1,231 @@
+/// Admin operations dashboard models: one snapshot of the job queue, schedulers, rate
+/// limiters, webhook deliveries, AI spend and database pool for on-call engineers
+
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+use serde::Serialize;
+use utoipa::ToSchema;
+use sqlx::FromRow;
+
+use crate::middleware::ip_rate_limiter::RateLimiterSnapshot;
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct OpsDashboard {
+    pub background_jobs: JobQueueSummary,
+    pub schedulers: SchedulerSummary,
+    /// Limiters of the instance that served the request; every instance counts on its own
+    pub rate_limiters: Vec<RateLimiterSnapshot>,
+    pub webhooks: WebhookDeliverySummary,
+    pub ai_spend: AiSpendSummary,
+    pub database: DbPoolSummary,
+    pub generated_at: DateTime<Utc>,
+}
+
+// ============================================================================
+// BACKGROUND JOBS
+// ============================================================================
+
+/// Queue depth and recent outcomes of one job type
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct JobTypeActivity {
+    pub job_type: String,
+    pub is_enabled: bool,
+    pub max_concurrency: i32,
+    pub queued: i64,
+    /// Queued jobs whose `run_at` has passed
+    pub due: i64,
+    pub running: i64,
+    pub succeeded_last_24h: i64,
+    pub failed_last_24h: i64,
+    pub oldest_due_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct JobQueueSummary {
+    pub job_types: Vec<JobTypeActivity>,
+    pub queued: i64,
+    pub running: i64,
+    pub failed_last_24h: i64,
+}
+
+impl JobQueueSummary {
+    pub fn new(job_types: Vec<JobTypeActivity>) -> Self {
+        Self {
+            queued: job_types.iter().map(|t| t.queued).sum(),
+            running: job_types.iter().map(|t| t.running).sum(),
+            failed_last_24h: job_types.iter().map(|t| t.failed_last_24h).sum(),
+            job_types,
+        }
+    }
+}
+
+// ============================================================================
+// SCHEDULERS
+// ============================================================================
+
+/// Last run of one alert scheduler check
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct ScheduledCheckStatus {
+    pub job_type: String,
+    pub is_enabled: bool,
+    pub interval_seconds: i32,
+    pub last_started_at: Option<DateTime<Utc>>,
+    pub last_completed_at: Option<DateTime<Utc>>,
+    pub last_status: Option<String>,
+    pub last_error: Option<String>,
+    pub next_run_at: DateTime<Utc>,
+    /// Enabled and more than one interval past its next run
+    pub overdue: bool,
+    /// Another instance currently holds the lease
+    pub running: bool,
+}
+
+/// Instance leading one scheduler (see `SchedulerLeader`)
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct SchedulerLeaderStatus {
+    pub scheduler: String,
+    pub instance_id: String,
+    pub acquired_at: DateTime<Utc>,
+    pub heartbeat_at: DateTime<Utc>,
+    /// Heartbeat overdue: the next contender takes over
+    pub stale: bool,
+}
+
+/// ERP sync scheduler of the instance that served the request
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ErpSchedulerStatus {
+    pub last_tick_at: DateTime<Utc>,
+    pub poll_minutes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SchedulerSummary {
+    pub alert_checks: Vec<ScheduledCheckStatus>,
+    pub leaders: Vec<SchedulerLeaderStatus>,
+    /// None before the scheduler's first tick
+    pub erp_sync: Option<ErpSchedulerStatus>,
+}
+
+// ============================================================================
+// WEBHOOKS
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct WebhookDeliverySummary {
+    pub window_hours: i64,
+    pub succeeded: i64,
+    pub failed: i64,
+    /// Waiting for their first attempt or a retry
+    pub pending: i64,
+    /// Failed share of the finished deliveries in the window, in percent
+    pub failure_rate_percent: f64,
+    /// Active endpoints whose last delivery failed
+    pub failing_endpoints: i64,
+    pub disabled_endpoints: i64,
+}
+
+// ============================================================================
+// AI SPEND
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AiSpendSummary {
+    pub month_start: DateTime<Utc>,
+    /// Successful Claude API calls; failed calls are not billed
+    pub calls: i64,
+    pub total_tokens: i64,
+    pub spend_usd: f64,
+    /// Spend at the end of the month if the month continues at its current rate
+    pub projected_month_usd: f64,
+}
+
+/// Start of the calendar month (UTC) containing `now`
+pub fn month_start(now: DateTime<Utc>) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0).single().unwrap_or(now)
+}
+
+/// Linear projection of the spend so far to the whole month
+pub fn project_month_spend(spend_usd: f64, now: DateTime<Utc>) -> f64 {
+    let start = month_start(now);
+    let next_month = if start.month() == 12 {
+        Utc.with_ymd_and_hms(start.year() + 1, 1, 1, 0, 0, 0)
+    } else {
+        Utc.with_ymd_and_hms(start.year(), start.month() + 1, 1, 0, 0, 0)
+    }
+    .single()
+    .unwrap_or(now);
+
+    let elapsed = (now - start).num_seconds().max(3600) as f64;
+    let total = (next_month - start).num_seconds() as f64;
+    round_cents(spend_usd * total / elapsed)
+}
+
+fn round_cents(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+// ============================================================================
+// DATABASE
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct DbPoolSummary {
+    pub max_connections: u32,
+    /// Open connections, idle or in use
+    pub size: u32,
+    pub idle: u32,
+    pub in_use: u32,
+    /// In-use share of `max_connections`, in percent
+    pub utilization_percent: f64,
+}
+
+impl DbPoolSummary {
+    pub fn new(max_connections: u32, size: u32, idle: usize) -> Self {
+        let in_use = size.saturating_sub(idle as u32);
+        Self {
+            max_connections,
+            size,
+            idle: idle as u32,
+            in_use,
+            utilization_percent: share_percent(in_use as i64, max_connections as i64),
+        }
+    }
+}
+
+/// `part` as a share of `whole` in percent, with one decimal; 0 when `whole` is 0
+pub fn share_percent(part: i64, whole: i64) -> f64 {
+    if whole <= 0 {
+        return 0.0;
+    }
+    (part as f64 / whole as f64 * 1000.0).round() / 10.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_share_percent() {
+        assert_eq!(share_percent(1, 3), 33.3);
+        assert_eq!(share_percent(5, 5), 100.0);
+        assert_eq!(share_percent(3, 0), 0.0);
+    }
+
+    #[test]
+    fn test_db_pool_summary() {
+        let pool = DbPoolSummary::new(20, 8, 3);
+        assert_eq!(pool.in_use, 5);
+        assert_eq!(pool.utilization_percent, 25.0);
+    }
+
+    #[test]
+    fn test_project_month_spend() {
+        // Halfway through a 30 day month
+        let now = Utc.with_ymd_and_hms(2026, 11, 16, 0, 0, 0).unwrap();
+        assert_eq!(month_start(now), Utc.with_ymd_and_hms(2026, 11, 1, 0, 0, 0).unwrap());
+        assert_eq!(project_month_spend(12.5, now), 25.0);
+
+        // December rolls over into the next year
+        let now = Utc.with_ymd_and_hms(2026, 12, 1, 0, 0, 0).unwrap();
+        assert!(project_month_spend(1.0, now) > 1.0);
+    }
+}
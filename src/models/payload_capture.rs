@@ -0,0 +1,270 @@
+/// Payload capture rules and the request/response bodies they capture
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Longest a rule may capture for; longer investigations open a new rule
+pub const MAX_CAPTURE_HOURS: i64 = 7 * 24;
+
+pub const DEFAULT_RETENTION_DAYS: i32 = 30;
+pub const MAX_RETENTION_DAYS: i32 = 90;
+
+const MAX_REASON_LENGTH: usize = 1000;
+
+const METHODS: &[&str] = &["GET", "POST", "PUT", "PATCH", "DELETE"];
+
+// ============================================================================
+// Database Models
+// ============================================================================
+
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct PayloadCaptureRule {
+    pub id: Uuid,
+    /// Capture this user's requests; every user when None
+    pub user_id: Option<Uuid>,
+    /// Route template or path prefix; every route when None
+    pub route: Option<String>,
+    /// Every method when None
+    pub method: Option<String>,
+    pub reason: String,
+    pub retention_days: i32,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub capture_until: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub revoked_by: Option<Uuid>,
+}
+
+impl PayloadCaptureRule {
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.revoked_at.is_none() && self.capture_until > now
+    }
+
+    /// Whether a request is captured by this rule. `route` is the matched route
+    /// template, `path` the raw path; a rule route matches either exactly or as a
+    /// prefix of the path.
+    pub fn matches(&self, user_id: Option<Uuid>, method: &str, route: &str, path: &str) -> bool {
+        self.user_id.is_none_or(|rule_user| user_id == Some(rule_user))
+            && self.method.as_deref().is_none_or(|rule_method| rule_method == method)
+            && self
+                .route
+                .as_deref()
+                .is_none_or(|rule_route| rule_route == route || path.starts_with(rule_route))
+    }
+}
+
+/// A capture without its bodies, for listings
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct PayloadCaptureSummary {
+    pub id: Uuid,
+    pub rule_id: Uuid,
+    pub request_id: Option<Uuid>,
+    pub user_id: Option<Uuid>,
+    pub method: String,
+    pub route: String,
+    pub status: i16,
+    pub latency_ms: i32,
+    pub truncated: bool,
+    pub captured_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct PayloadCapture {
+    pub id: Uuid,
+    pub rule_id: Uuid,
+    pub request_id: Option<Uuid>,
+    pub user_id: Option<Uuid>,
+    pub method: String,
+    pub route: String,
+    pub path: String,
+    pub query: Option<String>,
+    pub status: i16,
+    pub latency_ms: i32,
+    pub request_content_type: Option<String>,
+    pub request_body: Option<String>,
+    pub response_content_type: Option<String>,
+    pub response_body: Option<String>,
+    pub truncated: bool,
+    pub captured_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// A captured exchange, redacted, waiting to be stored
+#[derive(Debug, Clone)]
+pub struct NewPayloadCapture {
+    pub rule_id: Uuid,
+    pub retention_days: i32,
+    pub request_id: Option<Uuid>,
+    pub user_id: Option<Uuid>,
+    pub method: String,
+    pub route: String,
+    pub path: String,
+    pub query: Option<String>,
+    pub status: u16,
+    pub latency_ms: i32,
+    pub request_content_type: Option<String>,
+    pub request_body: Option<String>,
+    pub response_content_type: Option<String>,
+    pub response_body: Option<String>,
+    pub truncated: bool,
+}
+
+// ============================================================================
+// Request Models
+// ============================================================================
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreatePayloadCaptureRuleRequest {
+    pub user_id: Option<Uuid>,
+    pub route: Option<String>,
+    pub method: Option<String>,
+    pub reason: String,
+    /// How long to capture for, at most `MAX_CAPTURE_HOURS`
+    pub duration_hours: i64,
+    /// How long captures are kept; `DEFAULT_RETENTION_DAYS` when omitted
+    pub retention_days: Option<i32>,
+}
+
+/// A create request after validation
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidatedCaptureRule {
+    pub user_id: Option<Uuid>,
+    pub route: Option<String>,
+    pub method: Option<String>,
+    pub reason: String,
+    pub retention_days: i32,
+    pub capture_until: DateTime<Utc>,
+}
+
+impl CreatePayloadCaptureRuleRequest {
+    pub fn validate(&self, now: DateTime<Utc>) -> Result<ValidatedCaptureRule, String> {
+        let route = self.route.as_deref().map(str::trim).filter(|route| !route.is_empty());
+        if self.user_id.is_none() && route.is_none() {
+            return Err("Provide user_id, route or both; capturing all traffic is not allowed".to_string());
+        }
+        if let Some(route) = route {
+            if !route.starts_with("/api/") {
+                return Err("route must be an /api/ route template or path prefix".to_string());
+            }
+        }
+
+        let method = self.method.as_deref().map(|method| method.trim().to_uppercase());
+        if let Some(method) = &method {
+            if !METHODS.contains(&method.as_str()) {
+                return Err(format!("method must be one of {}", METHODS.join(", ")));
+            }
+        }
+
+        let reason = self.reason.trim();
+        if reason.is_empty() {
+            return Err("reason is required".to_string());
+        }
+        if reason.len() > MAX_REASON_LENGTH {
+            return Err(format!("reason must be at most {} characters", MAX_REASON_LENGTH));
+        }
+
+        if self.duration_hours < 1 || self.duration_hours > MAX_CAPTURE_HOURS {
+            return Err(format!("duration_hours must be between 1 and {}", MAX_CAPTURE_HOURS));
+        }
+
+        let retention_days = self.retention_days.unwrap_or(DEFAULT_RETENTION_DAYS);
+        if !(1..=MAX_RETENTION_DAYS).contains(&retention_days) {
+            return Err(format!("retention_days must be between 1 and {}", MAX_RETENTION_DAYS));
+        }
+
+        Ok(ValidatedCaptureRule {
+            user_id: self.user_id,
+            route: route.map(str::to_string),
+            method,
+            reason: reason.to_string(),
+            retention_days,
+            capture_until: now + Duration::hours(self.duration_hours),
+        })
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PayloadCaptureQuery {
+    pub rule_id: Option<Uuid>,
+    pub user_id: Option<Uuid>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(user_id: Option<Uuid>, route: Option<&str>) -> CreatePayloadCaptureRuleRequest {
+        CreatePayloadCaptureRuleRequest {
+            user_id,
+            route: route.map(str::to_string),
+            method: Some("post".to_string()),
+            reason: " Case 2291: suspected diversion ".to_string(),
+            duration_hours: 48,
+            retention_days: None,
+        }
+    }
+
+    #[test]
+    fn test_rule_validation() {
+        let now = Utc::now();
+        let rule = request(None, Some("/api/inquiries/")).validate(now).unwrap();
+        assert_eq!(rule.method.as_deref(), Some("POST"));
+        assert_eq!(rule.reason, "Case 2291: suspected diversion");
+        assert_eq!(rule.retention_days, DEFAULT_RETENTION_DAYS);
+        assert_eq!(rule.capture_until, now + Duration::hours(48));
+
+        assert!(request(None, None).validate(now).is_err());
+        assert!(request(None, Some("  ")).validate(now).is_err());
+        assert!(request(None, Some("/metrics")).validate(now).is_err());
+
+        let mut too_long = request(Some(Uuid::new_v4()), None);
+        too_long.duration_hours = MAX_CAPTURE_HOURS + 1;
+        assert!(too_long.validate(now).is_err());
+
+        let mut kept_too_long = request(Some(Uuid::new_v4()), None);
+        kept_too_long.retention_days = Some(MAX_RETENTION_DAYS + 1);
+        assert!(kept_too_long.validate(now).is_err());
+
+        let mut bad_method = request(Some(Uuid::new_v4()), None);
+        bad_method.method = Some("TRACE".to_string());
+        assert!(bad_method.validate(now).is_err());
+    }
+
+    #[test]
+    fn test_rule_matching() {
+        let user = Uuid::new_v4();
+        let rule = PayloadCaptureRule {
+            id: Uuid::new_v4(),
+            user_id: Some(user),
+            route: Some("/api/inventory/:id".to_string()),
+            method: None,
+            reason: "Case".to_string(),
+            retention_days: 30,
+            created_by: None,
+            created_at: Utc::now(),
+            capture_until: Utc::now() + Duration::hours(1),
+            revoked_at: None,
+            revoked_by: None,
+        };
+
+        assert!(rule.matches(Some(user), "PUT", "/api/inventory/:id", "/api/inventory/42"));
+        assert!(!rule.matches(None, "PUT", "/api/inventory/:id", "/api/inventory/42"));
+        assert!(!rule.matches(Some(Uuid::new_v4()), "PUT", "/api/inventory/:id", "/api/inventory/42"));
+        assert!(!rule.matches(Some(user), "GET", "/api/inventory", "/api/inventory"));
+
+        let prefix = PayloadCaptureRule { user_id: None, route: Some("/api/marketplace/".to_string()), method: Some("GET".to_string()), ..rule.clone() };
+        assert!(prefix.matches(None, "GET", "/api/marketplace/search", "/api/marketplace/search"));
+        assert!(!prefix.matches(None, "POST", "/api/marketplace/search", "/api/marketplace/search"));
+
+        assert!(rule.is_active(Utc::now()));
+        assert!(!PayloadCaptureRule { revoked_at: Some(Utc::now()), ..rule.clone() }.is_active(Utc::now()));
+        assert!(!rule.is_active(Utc::now() + Duration::hours(2)));
+    }
+}
@@ -1,9 +1,32 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 use sqlx::FromRow;
 use uuid::Uuid;
 use validator::Validate;
 
+use crate::models::{openfda::OpenFdaCatalogEntry, openfda_recall::extract_ndcs};
+
+/// Catalog segment a product belongs to
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProductDomain {
+    #[default]
+    HumanDrug,
+    Veterinary,
+    MedicalDevice,
+}
+
+impl ProductDomain {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProductDomain::HumanDrug => "human_drug",
+            ProductDomain::Veterinary => "veterinary",
+            ProductDomain::MedicalDevice => "medical_device",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Pharmaceutical {
     pub id: Uuid,
@@ -16,10 +39,14 @@ pub struct Pharmaceutical {
     pub strength: Option<String>,
     pub dosage_form: Option<String>,
     pub storage_requirements: Option<String>,
+    pub product_domain: String,
+    pub udi_di: Option<String>,
+    pub device_class: Option<String>,
+    pub target_species: Option<Vec<String>>,
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct CreatePharmaceuticalRequest {
     #[validate(length(min = 2, message = "Brand name must be at least 2 characters"))]
     pub brand_name: String,
@@ -34,9 +61,21 @@ pub struct CreatePharmaceuticalRequest {
     pub strength: Option<String>,
     pub dosage_form: Option<String>,
     pub storage_requirements: Option<String>,
+    /// Defaults to human_drug
+    #[serde(default)]
+    pub product_domain: Option<ProductDomain>,
+    /// GUDID primary device identifier (required for medical devices)
+    #[serde(default)]
+    #[validate(length(max = 50, message = "UDI-DI too long"))]
+    pub udi_di: Option<String>,
+    #[serde(default)]
+    pub device_class: Option<String>,
+    #[serde(default)]
+    pub target_species: Option<Vec<String>>,
 }
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct SearchPharmaceuticalRequest {
     pub query: Option<String>,
     pub brand_name: Option<String>,
@@ -44,11 +83,15 @@ pub struct SearchPharmaceuticalRequest {
     pub manufacturer: Option<String>,
     pub category: Option<String>,
     pub ndc_code: Option<String>,
+    pub product_domain: Option<ProductDomain>,
+    pub udi_di: Option<String>,
+    /// Comma-separated allergen classes to exclude, e.g. `lactose-free,gelatin-free`
+    pub free_of: Option<String>,
     pub limit: Option<i64>,
     pub offset: Option<i64>,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, ToSchema)]
 pub struct PharmaceuticalResponse {
     pub id: Uuid,
     pub brand_name: String,
@@ -60,7 +103,22 @@ pub struct PharmaceuticalResponse {
     pub strength: Option<String>,
     pub dosage_form: Option<String>,
     pub storage_requirements: Option<String>,
+    pub product_domain: String,
+    pub udi_di: Option<String>,
+    pub device_class: Option<String>,
+    pub target_species: Option<Vec<String>>,
     pub created_at: DateTime<Utc>,
+    /// OpenFDA catalog entry of the product's NDC (`expand=catalog_entry`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub catalog_entry: Option<OpenFdaCatalogEntry>,
+}
+
+impl PharmaceuticalResponse {
+    /// Product NDC (labeler-product) of the product's NDC code, as the OpenFDA catalog keys it
+    pub fn product_ndc(&self) -> Option<String> {
+        let ndc = self.ndc_code.as_deref()?;
+        extract_ndcs(ndc).into_iter().next().map(|(product_ndc, _)| product_ndc)
+    }
 }
 
 impl From<Pharmaceutical> for PharmaceuticalResponse {
@@ -76,7 +134,12 @@ impl From<Pharmaceutical> for PharmaceuticalResponse {
             strength: pharma.strength,
             dosage_form: pharma.dosage_form,
             storage_requirements: pharma.storage_requirements,
+            product_domain: pharma.product_domain,
+            udi_di: pharma.udi_di,
+            device_class: pharma.device_class,
+            target_species: pharma.target_species,
             created_at: pharma.created_at,
+            catalog_entry: None,
         }
     }
 }
\ No newline at end of file
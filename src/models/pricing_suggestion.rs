@@ -0,0 +1,308 @@
+/// AI pricing suggestion models for marketplace listings
+
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+// ============================================================================
+// Database Models
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct AiPricingSuggestion {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub ndc_code: String,
+    pub expiry_from: NaiveDate,
+    pub expiry_to: NaiveDate,
+    pub quantity: i32,
+    pub suggested_unit_price: Decimal,
+    pub price_range_low: Option<Decimal>,
+    pub price_range_high: Option<Decimal>,
+    pub confidence: Decimal,
+    pub rationale: String,
+    pub market_snapshot: serde_json::Value,
+    pub ai_cost_usd: Decimal,
+    pub ai_tokens_used: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+// ============================================================================
+// API Request/Response Models
+// ============================================================================
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PricingSuggestionRequest {
+    pub ndc_code: String,
+    /// Expiry window of the lot(s) being listed; `expiry_to` defaults to `expiry_from`
+    pub expiry_from: NaiveDate,
+    pub expiry_to: Option<NaiveDate>,
+    pub quantity: i32,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PricingHistoryQuery {
+    pub ndc_code: Option<String>,
+    pub limit: Option<i64>,
+}
+
+// ============================================================================
+// Market Context
+// ============================================================================
+
+/// Distribution of unit prices from a set of comparables
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PriceStats {
+    pub count: usize,
+    pub min: Decimal,
+    pub p25: Decimal,
+    pub median: Decimal,
+    pub p75: Decimal,
+    pub max: Decimal,
+}
+
+impl PriceStats {
+    /// `None` when there are no prices
+    pub fn from_prices(prices: &[Decimal]) -> Option<Self> {
+        if prices.is_empty() {
+            return None;
+        }
+
+        let mut sorted = prices.to_vec();
+        sorted.sort();
+
+        Some(Self {
+            count: sorted.len(),
+            min: sorted[0],
+            p25: percentile(&sorted, 0.25),
+            median: percentile(&sorted, 0.5),
+            p75: percentile(&sorted, 0.75),
+            max: sorted[sorted.len() - 1],
+        })
+    }
+}
+
+/// Linear-interpolated percentile of sorted prices, rounded to cents
+fn percentile(sorted: &[Decimal], q: f64) -> Decimal {
+    let rank = q * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    let weight = Decimal::from_f64(rank - lower as f64).unwrap_or_default();
+
+    (sorted[lower] + (sorted[upper] - sorted[lower]) * weight).round_dp(2)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct MonthlyPrice {
+    /// First day of the month
+    pub month: NaiveDate,
+    pub median_unit_price: Decimal,
+    pub transactions: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecallSignal {
+    pub recall_number: String,
+    pub classification: Option<String>,
+    pub status: Option<String>,
+    pub report_date: Option<NaiveDate>,
+    pub reason_for_recall: Option<String>,
+}
+
+/// Everything the suggestion is based on, stored with it as the market snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PricingMarketContext {
+    pub ndc_code: String,
+    pub brand_name: Option<String>,
+    pub generic_name: Option<String>,
+    pub manufacturer: Option<String>,
+    pub strength: Option<String>,
+    pub dosage_form: Option<String>,
+    pub days_to_expiry: i64,
+    pub quantity: i32,
+
+    /// Active marketplace listings of the NDC expiring within the window
+    pub listings_in_window: Option<PriceStats>,
+    /// All active marketplace listings of the NDC
+    pub listings_all: Option<PriceStats>,
+    pub listed_units: i64,
+    /// Completed and pending transactions over the last 180 days
+    pub recent_transactions: Option<PriceStats>,
+    pub median_transaction_quantity: Option<i32>,
+    pub monthly_trend: Vec<MonthlyPrice>,
+
+    /// Supply and authorization signals
+    pub recalls: Vec<RecallSignal>,
+    pub fda_listing_expired: bool,
+    pub fda_marketing_category: Option<String>,
+    pub ema_authorization_statuses: Vec<String>,
+}
+
+impl PricingMarketContext {
+    /// Market prices the suggestion is checked against: in-window listings, otherwise
+    /// recent transactions, otherwise any listing
+    pub fn reference_prices(&self) -> Option<&PriceStats> {
+        self.listings_in_window
+            .as_ref()
+            .or(self.recent_transactions.as_ref())
+            .or(self.listings_all.as_ref())
+    }
+
+    pub fn data_points(&self) -> usize {
+        [&self.listings_all, &self.recent_transactions]
+            .iter()
+            .filter_map(|s| s.as_ref())
+            .map(|s| s.count)
+            .sum()
+    }
+}
+
+/// Highest confidence a suggestion can have with this many market data points: a price
+/// with little market evidence is mostly the model's reasoning
+pub fn confidence_cap(data_points: usize) -> f64 {
+    match data_points {
+        0 => 0.4,
+        1..=4 => 0.6,
+        5..=19 => 0.8,
+        _ => 0.95,
+    }
+}
+
+/// A suggested price and range after guardrails
+#[derive(Debug, Clone, PartialEq)]
+pub struct GuardedPrice {
+    pub price: Decimal,
+    pub low: Decimal,
+    pub high: Decimal,
+    /// The model's price was outside plausible market bounds and was clamped
+    pub clamped: bool,
+}
+
+/// Keep the AI's price within [half the lowest, 1.5x the highest] market price when
+/// there is market data, round to cents, and make the range contain the price
+pub fn guard_price(
+    price: f64,
+    low: Option<f64>,
+    high: Option<f64>,
+    reference: Option<&PriceStats>,
+) -> Option<GuardedPrice> {
+    let to_decimal = |v: f64| Decimal::from_f64(v).filter(|d| !d.is_sign_negative()).map(|d| d.round_dp(2));
+    let mut suggested = to_decimal(price).filter(|p| !p.is_zero())?;
+    let mut clamped = false;
+
+    if let Some(stats) = reference {
+        let floor = (stats.min * Decimal::new(5, 1)).round_dp(2);
+        let ceiling = (stats.max * Decimal::new(15, 1)).round_dp(2);
+        if suggested < floor || suggested > ceiling {
+            suggested = suggested.clamp(floor, ceiling);
+            clamped = true;
+        }
+    }
+
+    let low = low.and_then(to_decimal).unwrap_or(suggested).min(suggested);
+    let high = high.and_then(to_decimal).unwrap_or(suggested).max(suggested);
+
+    Some(GuardedPrice { price: suggested, low, high, clamped })
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PricingSuggestionResponse {
+    pub id: Uuid,
+    pub ndc_code: String,
+    pub expiry_from: NaiveDate,
+    pub expiry_to: NaiveDate,
+    pub quantity: i32,
+    pub suggested_unit_price: Decimal,
+    pub price_range_low: Option<Decimal>,
+    pub price_range_high: Option<Decimal>,
+    pub confidence: f64,
+    pub rationale: String,
+    pub market_snapshot: serde_json::Value,
+    pub ai_cost_usd: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<AiPricingSuggestion> for PricingSuggestionResponse {
+    fn from(s: AiPricingSuggestion) -> Self {
+        Self {
+            id: s.id,
+            ndc_code: s.ndc_code,
+            expiry_from: s.expiry_from,
+            expiry_to: s.expiry_to,
+            quantity: s.quantity,
+            suggested_unit_price: s.suggested_unit_price,
+            price_range_low: s.price_range_low,
+            price_range_high: s.price_range_high,
+            confidence: s.confidence.to_f64().unwrap_or(0.0),
+            rationale: s.rationale,
+            market_snapshot: s.market_snapshot,
+            ai_cost_usd: s.ai_cost_usd.to_string(),
+            created_at: s.created_at,
+        }
+    }
+}
+
+// ============================================================================
+// AI Response Models
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct AiPricingResponse {
+    pub suggested_unit_price: f64,
+    pub price_range_low: Option<f64>,
+    pub price_range_high: Option<f64>,
+    pub confidence: f64,
+    pub rationale: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn stats(prices: &[Decimal]) -> PriceStats {
+        PriceStats::from_prices(prices).unwrap()
+    }
+
+    #[test]
+    fn test_price_stats() {
+        let s = stats(&[dec!(10), dec!(40), dec!(20), dec!(30)]);
+
+        assert_eq!(s.count, 4);
+        assert_eq!(s.min, dec!(10));
+        assert_eq!(s.median, dec!(25));
+        assert_eq!(s.p25, dec!(17.5));
+        assert_eq!(s.p75, dec!(32.5));
+        assert_eq!(s.max, dec!(40));
+        assert!(PriceStats::from_prices(&[]).is_none());
+    }
+
+    #[test]
+    fn test_guard_price() {
+        let market = stats(&[dec!(10), dec!(20)]);
+
+        let guarded = guard_price(18.456, Some(15.0), Some(22.0), Some(&market)).unwrap();
+        assert_eq!(guarded, GuardedPrice { price: dec!(18.46), low: dec!(15), high: dec!(22), clamped: false });
+
+        // Far above the market: clamped to 1.5x the highest price, range widened to contain it
+        let guarded = guard_price(100.0, Some(80.0), None, Some(&market)).unwrap();
+        assert_eq!(guarded.price, dec!(30));
+        assert_eq!(guarded.low, dec!(30));
+        assert!(guarded.clamped);
+
+        assert!(guard_price(0.0, None, None, None).is_none());
+        assert!(guard_price(-5.0, None, None, None).is_none());
+    }
+
+    #[test]
+    fn test_confidence_cap() {
+        assert_eq!(confidence_cap(0), 0.4);
+        assert_eq!(confidence_cap(3), 0.6);
+        assert_eq!(confidence_cap(50), 0.95);
+    }
+}
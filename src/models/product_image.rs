@@ -0,0 +1,112 @@
+/// Product image models for catalog pharmaceuticals
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ModerationStatus {
+    Pending,
+    Approved,
+    Rejected,
+}
+
+impl ModerationStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ModerationStatus::Pending => "pending",
+            ModerationStatus::Approved => "approved",
+            ModerationStatus::Rejected => "rejected",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PharmaceuticalImage {
+    pub id: Uuid,
+    pub pharmaceutical_id: Uuid,
+    pub uploaded_by: Option<Uuid>,
+    pub file_path: String,
+    pub thumbnail_path: String,
+    pub original_filename: Option<String>,
+    pub content_type: String,
+    pub file_size_bytes: i64,
+    pub file_hash: String,
+    pub width: i32,
+    pub height: i32,
+    pub alt_text: Option<String>,
+    pub is_primary: bool,
+    pub moderation_status: String,
+    pub moderated_by: Option<Uuid>,
+    pub moderated_at: Option<DateTime<Utc>>,
+    pub rejection_reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Public view of an image; storage paths are never exposed
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct PharmaceuticalImageResponse {
+    pub id: Uuid,
+    pub pharmaceutical_id: Uuid,
+    pub url: String,
+    pub thumbnail_url: String,
+    pub content_type: String,
+    pub file_size_bytes: i64,
+    pub width: i32,
+    pub height: i32,
+    pub alt_text: Option<String>,
+    pub is_primary: bool,
+    pub moderation_status: String,
+    pub rejection_reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<PharmaceuticalImage> for PharmaceuticalImageResponse {
+    fn from(image: PharmaceuticalImage) -> Self {
+        let base = format!(
+            "/api/pharmaceuticals/{}/images/{}",
+            image.pharmaceutical_id, image.id
+        );
+
+        Self {
+            id: image.id,
+            pharmaceutical_id: image.pharmaceutical_id,
+            url: format!("{}/file", base),
+            thumbnail_url: format!("{}/thumbnail", base),
+            content_type: image.content_type,
+            file_size_bytes: image.file_size_bytes,
+            width: image.width,
+            height: image.height,
+            alt_text: image.alt_text,
+            is_primary: image.is_primary,
+            moderation_status: image.moderation_status,
+            rejection_reason: image.rejection_reason,
+            created_at: image.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ModerateImageRequest {
+    pub status: ModerationStatus,
+    pub rejection_reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListImagesQuery {
+    /// Admins and uploaders may include pending/rejected images
+    #[serde(default)]
+    pub include_unmoderated: bool,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ModerationQueueQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
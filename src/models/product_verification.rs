@@ -0,0 +1,306 @@
+/// Product verification models: serialized inventory and VRS-style lookups of a
+/// product identifier (GTIN, serial, lot, expiry)
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Default response-time SLA of a lookup; VRS responders are expected to answer in
+/// under a second
+pub const DEFAULT_VERIFICATION_SLA_MS: i32 = 1000;
+
+/// Serial numbers registered per request
+pub const MAX_SERIALS_PER_REQUEST: usize = 5000;
+
+/// Lookup outcome. Anything that does not match a saleable unit (unknown GTIN or
+/// serial, lot/expiry mismatch, decommissioned, expired) is not_found, with the reason
+/// in additional_info.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum VerificationStatus {
+    Verified,
+    NotFound,
+    Recalled,
+}
+
+impl VerificationStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VerificationStatus::Verified => "verified",
+            VerificationStatus::NotFound => "not_found",
+            VerificationStatus::Recalled => "recalled",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum VerificationDetail {
+    UnknownSerial,
+    LotMismatch,
+    ExpiryMismatch,
+    Decommissioned,
+    Expired,
+    Recall,
+}
+
+impl VerificationDetail {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VerificationDetail::UnknownSerial => "unknown_serial",
+            VerificationDetail::LotMismatch => "lot_mismatch",
+            VerificationDetail::ExpiryMismatch => "expiry_mismatch",
+            VerificationDetail::Decommissioned => "decommissioned",
+            VerificationDetail::Expired => "expired",
+            VerificationDetail::Recall => "recall",
+        }
+    }
+}
+
+// ============================================================================
+// DATABASE MODELS
+// ============================================================================
+
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct InventorySerialNumber {
+    pub id: Uuid,
+    pub inventory_id: Uuid,
+    pub user_id: Uuid,
+    pub gtin: String,
+    pub serial_number: String,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A registered serial with the lot data it is checked against
+#[derive(Debug, Clone, FromRow)]
+pub struct SerializedUnit {
+    pub serial_id: Uuid,
+    pub status: String,
+    pub batch_number: String,
+    pub expiry_date: NaiveDate,
+    pub ndc_code: Option<String>,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct ProductVerificationRecord {
+    pub id: Uuid,
+    pub correlation_id: Option<String>,
+    pub gtin: String,
+    pub serial_number: String,
+    pub lot_number: String,
+    pub expiry_date: NaiveDate,
+    pub status: String,
+    pub additional_info: Option<String>,
+    pub recall_number: Option<String>,
+    pub response_time_ms: i32,
+    pub within_sla: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+// ============================================================================
+// REQUEST / RESPONSE MODELS
+// ============================================================================
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct VerificationLookupRequest {
+    #[validate(length(max = 100, message = "Correlation id must be at most 100 characters"))]
+    pub correlation_id: Option<String>,
+    pub gtin: String,
+    #[validate(length(min = 1, max = 20, message = "Serial number must be 1-20 characters"))]
+    pub serial_number: String,
+    #[validate(length(min = 1, max = 100, message = "Lot number must be 1-100 characters"))]
+    pub lot_number: String,
+    pub expiry_date: NaiveDate,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct VerificationLookupResponse {
+    pub verification_id: Uuid,
+    pub correlation_id: Option<String>,
+    pub gtin: String,
+    pub serial_number: String,
+    pub status: VerificationStatus,
+    pub verified: bool,
+    pub additional_info: Option<VerificationDetail>,
+    pub recall_number: Option<String>,
+    pub response_time_ms: i32,
+    pub within_sla: bool,
+    pub responded_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct RegisterSerialNumbersRequest {
+    pub inventory_id: Uuid,
+    pub gtin: String,
+    #[validate(length(min = 1, message = "At least one serial number is required"))]
+    pub serial_numbers: Vec<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RegisterSerialNumbersResponse {
+    pub registered: usize,
+    /// Serials already registered for this GTIN (skipped)
+    pub duplicates: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct VerificationHistoryQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+// ============================================================================
+// GTIN / MATCHING
+// ============================================================================
+
+/// Check a GTIN-14 (digits and GS1 check digit). GTIN-12/13 are zero-padded to 14.
+pub fn normalize_gtin(gtin: &str) -> Result<String, String> {
+    let gtin = gtin.trim();
+    if !(12..=14).contains(&gtin.len()) || !gtin.chars().all(|c| c.is_ascii_digit()) {
+        return Err(format!("Invalid GTIN '{}': expected 12-14 digits", gtin));
+    }
+    let gtin = format!("{:0>14}", gtin);
+
+    let digits: Vec<u32> = gtin.chars().filter_map(|c| c.to_digit(10)).collect();
+    let sum: u32 = digits[..13]
+        .iter()
+        .enumerate()
+        .map(|(i, d)| if i % 2 == 0 { d * 3 } else { *d })
+        .sum();
+    if (10 - sum % 10) % 10 != digits[13] {
+        return Err(format!("Invalid GTIN '{}': check digit does not match", gtin));
+    }
+    Ok(gtin)
+}
+
+/// Whether a US pharmaceutical GTIN-14 (indicator, "03", NDC-10, check digit) encodes
+/// the given NDC. NDCs that are not 10 digits (e.g. product-only codes) cannot be checked
+/// and are accepted.
+pub fn gtin_matches_ndc(gtin: &str, ndc: &str) -> bool {
+    let ndc_digits: String = ndc.chars().filter(|c| c.is_ascii_digit()).collect();
+    if ndc_digits.len() != 10 || gtin.len() != 14 {
+        return true;
+    }
+    &gtin[1..3] == "03" && gtin[3..13] == ndc_digits
+}
+
+/// Compare a product identifier with the registered unit. Recalls win over everything
+/// else; the rest must match exactly for the unit to be verified.
+pub fn evaluate_verification(
+    request: &VerificationLookupRequest,
+    unit: Option<&SerializedUnit>,
+    recalled: bool,
+    today: NaiveDate,
+) -> (VerificationStatus, Option<VerificationDetail>) {
+    let Some(unit) = unit else {
+        return (VerificationStatus::NotFound, Some(VerificationDetail::UnknownSerial));
+    };
+    if recalled {
+        return (VerificationStatus::Recalled, Some(VerificationDetail::Recall));
+    }
+
+    let detail = if !unit.batch_number.trim().eq_ignore_ascii_case(request.lot_number.trim()) {
+        Some(VerificationDetail::LotMismatch)
+    } else if unit.expiry_date != request.expiry_date {
+        Some(VerificationDetail::ExpiryMismatch)
+    } else if unit.status == "decommissioned" {
+        Some(VerificationDetail::Decommissioned)
+    } else if unit.expiry_date < today {
+        Some(VerificationDetail::Expired)
+    } else {
+        None
+    };
+
+    match detail {
+        Some(detail) => (VerificationStatus::NotFound, Some(detail)),
+        None => (VerificationStatus::Verified, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request() -> VerificationLookupRequest {
+        VerificationLookupRequest {
+            correlation_id: None,
+            gtin: "00300710155237".to_string(),
+            serial_number: "SN0001".to_string(),
+            lot_number: "lot-42a".to_string(),
+            expiry_date: NaiveDate::from_ymd_opt(2027, 3, 31).unwrap(),
+        }
+    }
+
+    fn unit() -> SerializedUnit {
+        SerializedUnit {
+            serial_id: Uuid::new_v4(),
+            status: "shipped".to_string(),
+            batch_number: "LOT-42A".to_string(),
+            expiry_date: NaiveDate::from_ymd_opt(2027, 3, 31).unwrap(),
+            ndc_code: Some("0071-0155-23".to_string()),
+        }
+    }
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, 1).unwrap()
+    }
+
+    #[test]
+    fn test_normalize_gtin() {
+        assert_eq!(normalize_gtin("00300710155237").unwrap(), "00300710155237");
+        assert_eq!(normalize_gtin("300710155237").unwrap(), "00300710155237");
+        assert!(normalize_gtin("00300710155238").is_err());
+        assert!(normalize_gtin("0030071015523X").is_err());
+    }
+
+    #[test]
+    fn test_gtin_matches_ndc() {
+        assert!(gtin_matches_ndc("00300710155237", "0071-0155-23"));
+        assert!(!gtin_matches_ndc("00300710155237", "0071-0156-23"));
+        assert!(gtin_matches_ndc("00300710155237", "0071-0155"));
+    }
+
+    #[test]
+    fn test_evaluate_verification() {
+        let request = request();
+
+        assert_eq!(
+            evaluate_verification(&request, Some(&unit()), false, today()),
+            (VerificationStatus::Verified, None)
+        );
+        assert_eq!(
+            evaluate_verification(&request, None, false, today()),
+            (VerificationStatus::NotFound, Some(VerificationDetail::UnknownSerial))
+        );
+        assert_eq!(
+            evaluate_verification(&request, Some(&unit()), true, today()),
+            (VerificationStatus::Recalled, Some(VerificationDetail::Recall))
+        );
+
+        let mut other_lot = unit();
+        other_lot.batch_number = "LOT-43".to_string();
+        assert_eq!(
+            evaluate_verification(&request, Some(&other_lot), false, today()).1,
+            Some(VerificationDetail::LotMismatch)
+        );
+
+        let mut decommissioned = unit();
+        decommissioned.status = "decommissioned".to_string();
+        assert_eq!(
+            evaluate_verification(&request, Some(&decommissioned), false, today()).1,
+            Some(VerificationDetail::Decommissioned)
+        );
+
+        let later = NaiveDate::from_ymd_opt(2027, 4, 1).unwrap();
+        assert_eq!(
+            evaluate_verification(&request, Some(&unit()), false, later).1,
+            Some(VerificationDetail::Expired)
+        );
+    }
+}
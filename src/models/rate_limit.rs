@@ -0,0 +1,14 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::middleware::ip_rate_limiter::RateLimitStatus;
+use crate::models::ai_quota::AiQuotaUsage;
+
+/// Everything that can answer a caller with 429, as of now
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct RateLimitsResponse {
+    /// Request budgets of the caller's IP address, one per limiter
+    pub rate_limits: Vec<RateLimitStatus>,
+    /// The account's AI plan and per-feature monthly quotas
+    pub ai_quota: AiQuotaUsage,
+}
@@ -0,0 +1,95 @@
+/// Real-time delivery models
+///
+/// New notifications and inquiry messages are announced by database triggers with
+/// `pg_notify` on `REALTIME_CHANNEL`, so every API instance learns about rows written by
+/// any instance. Each instance loads the row once and fans it out to its own
+/// `GET /api/alerts/stream` subscribers.
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Postgres NOTIFY channel the triggers publish on
+pub const REALTIME_CHANNEL: &str = "atlas_realtime";
+
+/// Seconds between heartbeats on an idle stream (also how often the token is re-checked)
+pub const STREAM_HEARTBEAT_SECONDS: u64 = 15;
+
+/// Concurrent streams per user (one per open tab/device)
+pub const MAX_STREAMS_PER_USER: usize = 5;
+
+/// Events buffered per instance; a subscriber falling further behind is told to resync
+pub const REALTIME_BUFFER_SIZE: usize = 1024;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RealtimeEventKind {
+    Notification,
+    InquiryMessage,
+}
+
+impl RealtimeEventKind {
+    /// SSE `event:` name
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RealtimeEventKind::Notification => "notification",
+            RealtimeEventKind::InquiryMessage => "inquiry_message",
+        }
+    }
+}
+
+/// NOTIFY payload written by the triggers: which row, and who may see it
+#[derive(Debug, Clone, Deserialize)]
+pub struct RealtimeNotice {
+    pub kind: RealtimeEventKind,
+    pub id: Uuid,
+    pub user_ids: Vec<Uuid>,
+}
+
+/// Loaded event fanned out to local subscribers
+#[derive(Debug, Clone)]
+pub struct RealtimeEvent {
+    pub kind: RealtimeEventKind,
+    pub id: Uuid,
+    pub user_ids: Vec<Uuid>,
+    pub data: serde_json::Value,
+}
+
+impl RealtimeEvent {
+    pub fn is_for(&self, user_id: Uuid) -> bool {
+        self.user_ids.contains(&user_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_notice() {
+        let user_id = Uuid::new_v4();
+        let payload = format!(
+            r#"{{"kind":"inquiry_message","id":"{}","user_ids":["{}"]}}"#,
+            Uuid::new_v4(),
+            user_id
+        );
+        let notice: RealtimeNotice = serde_json::from_str(&payload).unwrap();
+        assert_eq!(notice.kind, RealtimeEventKind::InquiryMessage);
+        assert_eq!(notice.user_ids, vec![user_id]);
+
+        assert!(serde_json::from_str::<RealtimeNotice>(r#"{"kind":"unknown","id":"x","user_ids":[]}"#).is_err());
+    }
+
+    #[test]
+    fn test_event_audience() {
+        let (buyer, seller) = (Uuid::new_v4(), Uuid::new_v4());
+        let event = RealtimeEvent {
+            kind: RealtimeEventKind::InquiryMessage,
+            id: Uuid::new_v4(),
+            user_ids: vec![buyer, seller],
+            data: serde_json::json!({}),
+        };
+        assert!(event.is_for(buyer));
+        assert!(event.is_for(seller));
+        assert!(!event.is_for(Uuid::new_v4()));
+    }
+}
@@ -0,0 +1,175 @@
+/// Regulator catalog models (UK MHRA, Health Canada DPD, Swissmedic)
+///
+/// Sources share the `regulator_catalog` table and the `catalog_sync_log` sync log;
+/// each source is fetched by a connector that maps its data to `RegulatorCatalogRecord`.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum RegulatorSource {
+    Mhra,
+    HealthCanada,
+    Swissmedic,
+}
+
+impl RegulatorSource {
+    pub const ALL: [RegulatorSource; 3] = [
+        RegulatorSource::Mhra,
+        RegulatorSource::HealthCanada,
+        RegulatorSource::Swissmedic,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RegulatorSource::Mhra => "mhra",
+            RegulatorSource::HealthCanada => "health_canada",
+            RegulatorSource::Swissmedic => "swissmedic",
+        }
+    }
+
+    /// Parse a source from a URL segment ("mhra", "health-canada", "health_canada", "swissmedic")
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().replace('-', "_").as_str() {
+            "mhra" => Some(RegulatorSource::Mhra),
+            "health_canada" | "hc" | "dpd" => Some(RegulatorSource::HealthCanada),
+            "swissmedic" => Some(RegulatorSource::Swissmedic),
+            _ => None,
+        }
+    }
+
+    /// ISO 3166-1 alpha-2 country the catalog covers
+    pub fn country(&self) -> &'static str {
+        match self {
+            RegulatorSource::Mhra => "GB",
+            RegulatorSource::HealthCanada => "CA",
+            RegulatorSource::Swissmedic => "CH",
+        }
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            RegulatorSource::Mhra => "UK MHRA",
+            RegulatorSource::HealthCanada => "Health Canada Drug Product Database",
+            RegulatorSource::Swissmedic => "Swissmedic",
+        }
+    }
+}
+
+impl std::fmt::Display for RegulatorSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+// ============================================================================
+// Database Models
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct RegulatorCatalogEntry {
+    pub id: Uuid,
+    pub source: String,
+    pub source_id: String,
+    pub country: String,
+    pub product_name: String,
+    pub active_ingredients: Vec<String>,
+    pub strength: Option<String>,
+    pub dosage_form: Option<String>,
+    pub route_of_administration: Vec<String>,
+    pub atc_code: Option<String>,
+    pub authorization_holder: Option<String>,
+    pub authorization_status: Option<String>,
+    pub authorization_date: Option<NaiveDate>,
+    pub raw_data: Option<serde_json::Value>,
+    pub last_synced_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// A product as produced by a connector, before it is stored
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegulatorCatalogRecord {
+    pub source_id: String,
+    pub product_name: String,
+    pub active_ingredients: Vec<String>,
+    pub strength: Option<String>,
+    pub dosage_form: Option<String>,
+    pub route_of_administration: Vec<String>,
+    pub atc_code: Option<String>,
+    pub authorization_holder: Option<String>,
+    pub authorization_status: Option<String>,
+    pub authorization_date: Option<NaiveDate>,
+    pub raw_data: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct CatalogSyncLog {
+    pub id: Uuid,
+    pub source: String,
+    pub sync_type: String,
+    pub status: String,
+    pub sync_started_at: DateTime<Utc>,
+    pub sync_completed_at: Option<DateTime<Utc>>,
+    pub last_heartbeat_at: Option<DateTime<Utc>>,
+    pub records_fetched: i32,
+    pub records_inserted: i32,
+    pub records_updated: i32,
+    pub records_skipped: i32,
+    pub records_failed: i32,
+    pub records_removed: i32,
+    pub api_response_time_ms: Option<i32>,
+    pub processing_time_ms: Option<i32>,
+    pub error_message: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    /// Set when guardrails marked the run `suspect`
+    pub anomalies: Option<serde_json::Value>,
+}
+
+// ============================================================================
+// Request/Response Models
+// ============================================================================
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RegulatorCatalogSearchRequest {
+    /// Matches product name, active ingredients, authorization holder and identifier
+    pub query: Option<String>,
+    pub ingredient: Option<String>,
+    pub atc_code: Option<String>,
+    pub authorization_status: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RegulatorCatalogStats {
+    pub source: String,
+    pub display_name: String,
+    pub country: String,
+    pub total_entries: i64,
+    pub last_synced_at: Option<DateTime<Utc>>,
+    pub last_sync: Option<CatalogSyncLog>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_source() {
+        assert_eq!(RegulatorSource::parse("MHRA"), Some(RegulatorSource::Mhra));
+        assert_eq!(RegulatorSource::parse("health-canada"), Some(RegulatorSource::HealthCanada));
+        assert_eq!(RegulatorSource::parse("health_canada"), Some(RegulatorSource::HealthCanada));
+        assert_eq!(RegulatorSource::parse("swissmedic"), Some(RegulatorSource::Swissmedic));
+        assert_eq!(RegulatorSource::parse("openfda"), None);
+
+        for source in RegulatorSource::ALL {
+            assert_eq!(RegulatorSource::parse(source.as_str()), Some(source));
+        }
+    }
+}
@@ -0,0 +1,569 @@
+/// Regulatory rules engine models: admin-configured jurisdiction rules and their
+/// evaluation against a trade (buyer and seller country, product, licenses held)
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use sqlx::FromRow;
+use std::collections::HashSet;
+use uuid::Uuid;
+use validator::Validate;
+use crate::models::{announcement::normalize_country_codes, license::LicenseType};
+
+/// DEA controlled substance schedules as listed in the openFDA catalog
+pub const DEA_SCHEDULES: [&str; 5] = ["CI", "CII", "CIII", "CIV", "CV"];
+
+/// Product domains a rule can be limited to
+pub const PRODUCT_DOMAINS: [&str; 3] = ["human_drug", "veterinary", "medical_device"];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleScope {
+    Any,
+    /// Buyer and seller in the same country
+    Domestic,
+    CrossBorder,
+}
+
+impl RuleScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RuleScope::Any => "any",
+            RuleScope::Domestic => "domestic",
+            RuleScope::CrossBorder => "cross_border",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RequirementType {
+    /// The product holds a marketing authorization in the buyer's country
+    ProductAuthorization,
+    /// The buyer holds a verified, unexpired license of the rule's license type
+    BuyerLicense,
+    /// The seller holds a verified, unexpired license of the rule's license type
+    SellerLicense,
+    /// Something the parties must take care of; never blocks
+    Notice,
+}
+
+impl RequirementType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RequirementType::ProductAuthorization => "product_authorization",
+            RequirementType::BuyerLicense => "buyer_license",
+            RequirementType::SellerLicense => "seller_license",
+            RequirementType::Notice => "notice",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "product_authorization" => Some(RequirementType::ProductAuthorization),
+            "buyer_license" => Some(RequirementType::BuyerLicense),
+            "seller_license" => Some(RequirementType::SellerLicense),
+            "notice" => Some(RequirementType::Notice),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleEnforcement {
+    /// An unmet requirement refuses the inquiry or transaction
+    Block,
+    /// An unmet requirement is listed as an action item
+    Warn,
+}
+
+impl RuleEnforcement {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RuleEnforcement::Block => "block",
+            RuleEnforcement::Warn => "warn",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RequirementStatus {
+    Met,
+    ActionRequired,
+    /// Can't be checked automatically (e.g. no catalog for the buyer's country)
+    Unverified,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EvaluationContext {
+    Inquiry,
+    Transaction,
+}
+
+impl EvaluationContext {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EvaluationContext::Inquiry => "inquiry",
+            EvaluationContext::Transaction => "transaction",
+        }
+    }
+}
+
+// ============================================================================
+// DATABASE MODELS
+// ============================================================================
+
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct RegulatoryRule {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub seller_countries: Vec<String>,
+    pub buyer_countries: Vec<String>,
+    pub scope: String,
+    pub product_domains: Vec<String>,
+    pub dea_schedules: Vec<String>,
+    pub requirement_type: String,
+    pub license_type: Option<String>,
+    pub enforcement: String,
+    pub guidance: Option<String>,
+    pub is_active: bool,
+    pub created_by: Option<Uuid>,
+    pub updated_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl RegulatoryRule {
+    /// Whether the rule's conditions match the trade. Country conditions never match
+    /// a party without a country.
+    pub fn applies_to(&self, trade: &TradeFacts) -> bool {
+        let country_matches = |countries: &[String], country: &Option<String>| {
+            countries.is_empty() || country.as_ref().map_or(false, |c| countries.contains(c))
+        };
+        if !country_matches(&self.seller_countries, &trade.seller_country)
+            || !country_matches(&self.buyer_countries, &trade.buyer_country)
+        {
+            return false;
+        }
+
+        let scope_matches = match (self.scope.as_str(), &trade.seller_country, &trade.buyer_country) {
+            ("any", _, _) => true,
+            ("domestic", Some(seller), Some(buyer)) => seller == buyer,
+            ("cross_border", Some(seller), Some(buyer)) => seller != buyer,
+            _ => false,
+        };
+        if !scope_matches {
+            return false;
+        }
+
+        if !self.product_domains.is_empty() && !self.product_domains.contains(&trade.product_domain) {
+            return false;
+        }
+        if !self.dea_schedules.is_empty()
+            && !trade.dea_schedule.as_ref().map_or(false, |s| self.dea_schedules.contains(s))
+        {
+            return false;
+        }
+
+        true
+    }
+
+    fn license_label(&self) -> &'static str {
+        self.license_type
+            .as_deref()
+            .and_then(LicenseType::parse)
+            .map_or("License", |t| t.label())
+    }
+
+    /// Check the rule's requirement against the trade
+    pub fn evaluate(&self, trade: &TradeFacts) -> RequirementResult {
+        let requirement_type = RequirementType::parse(&self.requirement_type).unwrap_or(RequirementType::Notice);
+        let buyer_country = trade.buyer_country.as_deref().unwrap_or("the buyer's country");
+
+        let (party, status, message) = match requirement_type {
+            RequirementType::ProductAuthorization => {
+                let (status, message) = match trade.authorized_in_buyer_country {
+                    Some(true) => (
+                        RequirementStatus::Met,
+                        format!("{} is authorized in {}", trade.product_name, buyer_country),
+                    ),
+                    Some(false) => (
+                        RequirementStatus::ActionRequired,
+                        format!("{} has no marketing authorization on record in {}", trade.product_name, buyer_country),
+                    ),
+                    None => (
+                        RequirementStatus::Unverified,
+                        format!("Authorization of {} in {} can't be checked automatically", trade.product_name, buyer_country),
+                    ),
+                };
+                (None, status, message)
+            }
+            RequirementType::BuyerLicense | RequirementType::SellerLicense => {
+                let (party, licenses) = if requirement_type == RequirementType::BuyerLicense {
+                    ("buyer", &trade.buyer_licenses)
+                } else {
+                    ("seller", &trade.seller_licenses)
+                };
+                let label = self.license_label();
+                let held = self.license_type.as_ref().map_or(false, |t| licenses.contains(t));
+                let (status, message) = if held {
+                    (RequirementStatus::Met, format!("The {} holds a verified {}", party, label.to_lowercase()))
+                } else {
+                    (RequirementStatus::ActionRequired, format!("The {} needs a verified {}", party, label.to_lowercase()))
+                };
+                (Some(party.to_string()), status, message)
+            }
+            RequirementType::Notice => (None, RequirementStatus::ActionRequired, self.name.clone()),
+        };
+
+        let blocking = self.enforcement == RuleEnforcement::Block.as_str()
+            && requirement_type != RequirementType::Notice
+            && status == RequirementStatus::ActionRequired;
+
+        RequirementResult {
+            rule_id: self.id,
+            rule_name: self.name.clone(),
+            requirement_type: requirement_type.as_str().to_string(),
+            party,
+            status,
+            blocking,
+            message,
+            guidance: self.guidance.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct RegulatoryRuleEvaluation {
+    pub id: Uuid,
+    pub context: String,
+    pub inquiry_id: Option<Uuid>,
+    pub transaction_id: Option<Uuid>,
+    pub inventory_id: Option<Uuid>,
+    pub buyer_id: Uuid,
+    pub seller_id: Uuid,
+    pub buyer_country: Option<String>,
+    pub seller_country: Option<String>,
+    pub blocked: bool,
+    pub requirements: serde_json::Value,
+    pub evaluated_at: DateTime<Utc>,
+}
+
+// ============================================================================
+// EVALUATION
+// ============================================================================
+
+/// What the rules are evaluated against
+#[derive(Debug, Clone)]
+pub struct TradeFacts {
+    pub inventory_id: Uuid,
+    pub buyer_id: Uuid,
+    pub seller_id: Uuid,
+    pub buyer_country: Option<String>,
+    pub seller_country: Option<String>,
+    pub product_name: String,
+    pub product_domain: String,
+    pub dea_schedule: Option<String>,
+    /// None: no regulator catalog covers the buyer's country
+    pub authorized_in_buyer_country: Option<bool>,
+    /// Types of the verified, unexpired licenses of each party
+    pub buyer_licenses: HashSet<String>,
+    pub seller_licenses: HashSet<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RequirementResult {
+    pub rule_id: Uuid,
+    pub rule_name: String,
+    pub requirement_type: String,
+    /// buyer or seller for license requirements
+    pub party: Option<String>,
+    pub status: RequirementStatus,
+    /// Unmet requirement of a blocking rule
+    pub blocking: bool,
+    pub message: String,
+    pub guidance: Option<String>,
+}
+
+/// Requirements of a trade, met ones included
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct RegulatoryEvaluation {
+    pub inventory_id: Uuid,
+    pub buyer_id: Uuid,
+    pub seller_id: Uuid,
+    pub buyer_country: Option<String>,
+    pub seller_country: Option<String>,
+    pub blocked: bool,
+    pub requirements: Vec<RequirementResult>,
+}
+
+impl RegulatoryEvaluation {
+    /// Error message listing what refuses the trade
+    pub fn blocking_summary(&self) -> String {
+        let unmet: Vec<String> = self
+            .requirements
+            .iter()
+            .filter(|r| r.blocking)
+            .map(|r| match &r.guidance {
+                Some(guidance) => format!("{}. {}", r.message, guidance),
+                None => r.message.clone(),
+            })
+            .collect();
+        format!("Regulatory requirements not met: {}", unmet.join("; "))
+    }
+}
+
+/// Evaluate the active rules that apply to the trade; blocking requirements first
+pub fn evaluate_rules(rules: &[RegulatoryRule], trade: &TradeFacts) -> RegulatoryEvaluation {
+    let mut requirements: Vec<RequirementResult> = rules
+        .iter()
+        .filter(|rule| rule.is_active && rule.applies_to(trade))
+        .map(|rule| rule.evaluate(trade))
+        .collect();
+    requirements.sort_by_key(|r| (!r.blocking, r.status == RequirementStatus::Met));
+
+    RegulatoryEvaluation {
+        inventory_id: trade.inventory_id,
+        buyer_id: trade.buyer_id,
+        seller_id: trade.seller_id,
+        buyer_country: trade.buyer_country.clone(),
+        seller_country: trade.seller_country.clone(),
+        blocked: requirements.iter().any(|r| r.blocking),
+        requirements,
+    }
+}
+
+// ============================================================================
+// REQUEST / RESPONSE MODELS
+// ============================================================================
+
+/// Creates a rule, or replaces one on update
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct RegulatoryRuleRequest {
+    #[validate(length(min = 3, max = 200, message = "Name must be 3-200 characters"))]
+    pub name: String,
+    #[validate(length(max = 2000, message = "Description must be at most 2000 characters"))]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub seller_countries: Vec<String>,
+    #[serde(default)]
+    pub buyer_countries: Vec<String>,
+    pub scope: Option<RuleScope>,
+    #[serde(default)]
+    pub product_domains: Vec<String>,
+    #[serde(default)]
+    pub dea_schedules: Vec<String>,
+    pub requirement_type: RequirementType,
+    /// Required for buyer_license / seller_license
+    pub license_type: Option<LicenseType>,
+    pub enforcement: RuleEnforcement,
+    #[validate(length(max = 2000, message = "Guidance must be at most 2000 characters"))]
+    pub guidance: Option<String>,
+    pub is_active: Option<bool>,
+}
+
+impl RegulatoryRuleRequest {
+    /// Normalize country codes and schedules and check the rule is consistent
+    pub fn normalize(mut self) -> Result<Self, String> {
+        self.name = self.name.trim().to_string();
+        self.seller_countries = normalize_country_codes(&self.seller_countries)?;
+        self.buyer_countries = normalize_country_codes(&self.buyer_countries)?;
+
+        for domain in &self.product_domains {
+            if !PRODUCT_DOMAINS.contains(&domain.as_str()) {
+                return Err(format!("Unknown product domain '{}'", domain));
+            }
+        }
+
+        let mut schedules: Vec<String> = Vec::with_capacity(self.dea_schedules.len());
+        for schedule in &self.dea_schedules {
+            let schedule = schedule.trim().to_ascii_uppercase();
+            if !DEA_SCHEDULES.contains(&schedule.as_str()) {
+                return Err(format!("Unknown DEA schedule '{}'", schedule));
+            }
+            if !schedules.contains(&schedule) {
+                schedules.push(schedule);
+            }
+        }
+        self.dea_schedules = schedules;
+
+        match self.requirement_type {
+            RequirementType::BuyerLicense | RequirementType::SellerLicense => {
+                if self.license_type.is_none() {
+                    return Err("License requirements need a license_type".to_string());
+                }
+            }
+            RequirementType::ProductAuthorization | RequirementType::Notice => self.license_type = None,
+        }
+        if self.requirement_type == RequirementType::Notice && self.enforcement == RuleEnforcement::Block {
+            return Err("Notices can't block trades".to_string());
+        }
+
+        Ok(self)
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RegulatoryRuleQuery {
+    pub include_inactive: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RegulatoryCheckRequest {
+    pub inventory_id: Uuid,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RegulatoryEvaluationQuery {
+    pub blocked: Option<bool>,
+    pub user_id: Option<Uuid>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(requirement_type: RequirementType, enforcement: RuleEnforcement) -> RegulatoryRule {
+        RegulatoryRule {
+            id: Uuid::new_v4(),
+            name: "Test rule".to_string(),
+            description: None,
+            seller_countries: vec![],
+            buyer_countries: vec![],
+            scope: "any".to_string(),
+            product_domains: vec![],
+            dea_schedules: vec![],
+            requirement_type: requirement_type.as_str().to_string(),
+            license_type: None,
+            enforcement: enforcement.as_str().to_string(),
+            guidance: None,
+            is_active: true,
+            created_by: None,
+            updated_by: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn trade(seller_country: Option<&str>, buyer_country: Option<&str>) -> TradeFacts {
+        TradeFacts {
+            inventory_id: Uuid::new_v4(),
+            buyer_id: Uuid::new_v4(),
+            seller_id: Uuid::new_v4(),
+            buyer_country: buyer_country.map(str::to_string),
+            seller_country: seller_country.map(str::to_string),
+            product_name: "Oxycontin".to_string(),
+            product_domain: "human_drug".to_string(),
+            dea_schedule: Some("CII".to_string()),
+            authorized_in_buyer_country: None,
+            buyer_licenses: HashSet::new(),
+            seller_licenses: HashSet::new(),
+        }
+    }
+
+    #[test]
+    fn test_scope_and_country_conditions() {
+        let mut cross_border = rule(RequirementType::Notice, RuleEnforcement::Warn);
+        cross_border.scope = "cross_border".to_string();
+        assert!(cross_border.applies_to(&trade(Some("US"), Some("DE"))));
+        assert!(!cross_border.applies_to(&trade(Some("US"), Some("US"))));
+        assert!(!cross_border.applies_to(&trade(None, Some("US"))));
+
+        let mut us_buyers = rule(RequirementType::Notice, RuleEnforcement::Warn);
+        us_buyers.buyer_countries = vec!["US".to_string()];
+        assert!(us_buyers.applies_to(&trade(Some("DE"), Some("US"))));
+        assert!(!us_buyers.applies_to(&trade(Some("US"), Some("DE"))));
+        assert!(!us_buyers.applies_to(&trade(Some("US"), None)));
+    }
+
+    #[test]
+    fn test_dea_schedule_condition() {
+        let mut controlled = rule(RequirementType::Notice, RuleEnforcement::Warn);
+        controlled.dea_schedules = vec!["CII".to_string()];
+        let mut facts = trade(Some("US"), Some("US"));
+        assert!(controlled.applies_to(&facts));
+
+        facts.dea_schedule = None;
+        assert!(!controlled.applies_to(&facts));
+    }
+
+    #[test]
+    fn test_missing_license_blocks() {
+        let mut dea = rule(RequirementType::BuyerLicense, RuleEnforcement::Block);
+        dea.license_type = Some("dea_registration".to_string());
+        let mut facts = trade(Some("US"), Some("US"));
+
+        let evaluation = evaluate_rules(&[dea.clone()], &facts);
+        assert!(evaluation.blocked);
+        assert_eq!(evaluation.requirements[0].party.as_deref(), Some("buyer"));
+        assert!(evaluation.blocking_summary().contains("The buyer needs a verified dea registration"));
+
+        facts.buyer_licenses.insert("dea_registration".to_string());
+        let evaluation = evaluate_rules(&[dea], &facts);
+        assert!(!evaluation.blocked);
+        assert_eq!(evaluation.requirements[0].status, RequirementStatus::Met);
+    }
+
+    #[test]
+    fn test_unverified_authorization_does_not_block() {
+        let authorization = rule(RequirementType::ProductAuthorization, RuleEnforcement::Block);
+        let mut facts = trade(Some("US"), Some("BR"));
+
+        let evaluation = evaluate_rules(&[authorization.clone()], &facts);
+        assert!(!evaluation.blocked);
+        assert_eq!(evaluation.requirements[0].status, RequirementStatus::Unverified);
+
+        facts.authorized_in_buyer_country = Some(false);
+        assert!(evaluate_rules(&[authorization], &facts).blocked);
+    }
+
+    #[test]
+    fn test_warnings_and_inactive_rules() {
+        let mut import = rule(RequirementType::BuyerLicense, RuleEnforcement::Warn);
+        import.license_type = Some("import_license".to_string());
+        let mut inactive = rule(RequirementType::BuyerLicense, RuleEnforcement::Block);
+        inactive.license_type = Some("dea_registration".to_string());
+        inactive.is_active = false;
+
+        let evaluation = evaluate_rules(&[import, inactive], &trade(Some("US"), Some("DE")));
+        assert!(!evaluation.blocked);
+        assert_eq!(evaluation.requirements.len(), 1);
+        assert_eq!(evaluation.requirements[0].status, RequirementStatus::ActionRequired);
+    }
+
+    #[test]
+    fn test_rule_request_normalization() {
+        let request = |requirement_type, license_type, enforcement| RegulatoryRuleRequest {
+            name: " Narcotics export permit ".to_string(),
+            description: None,
+            seller_countries: vec!["de".to_string()],
+            buyer_countries: vec![],
+            scope: Some(RuleScope::CrossBorder),
+            product_domains: vec![],
+            dea_schedules: vec!["cii".to_string(), "CII".to_string()],
+            requirement_type,
+            license_type,
+            enforcement,
+            guidance: None,
+            is_active: None,
+        };
+
+        let normalized = request(RequirementType::SellerLicense, Some(LicenseType::NarcoticsPermit), RuleEnforcement::Block)
+            .normalize()
+            .unwrap();
+        assert_eq!(normalized.name, "Narcotics export permit");
+        assert_eq!(normalized.seller_countries, vec!["DE"]);
+        assert_eq!(normalized.dea_schedules, vec!["CII"]);
+
+        assert!(request(RequirementType::SellerLicense, None, RuleEnforcement::Block).normalize().is_err());
+        assert!(request(RequirementType::Notice, None, RuleEnforcement::Block).normalize().is_err());
+    }
+}
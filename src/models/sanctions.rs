@@ -0,0 +1,704 @@
+/// Sanctions screening models: OFAC SDN and EU consolidated list entries, name matching,
+/// screening results and their admin review
+///
+/// Name normalization, scoring and list parsing are plain functions so they can be
+/// tested without a database or network; `SanctionsScreeningService` and
+/// `SanctionsListService` feed them stored and downloaded data.
+
+use std::collections::{BTreeMap, HashMap};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Tokens dropped before matching: legal forms and filler words
+const IGNORED_NAME_TOKENS: [&str; 36] = [
+    "ltd", "limited", "llc", "inc", "incorporated", "corp", "corporation", "co", "company",
+    "gmbh", "ag", "sa", "sas", "sarl", "srl", "spa", "bv", "nv", "plc", "llp", "lp", "oy",
+    "ab", "kg", "ooo", "oao", "zao", "pjsc", "jsc", "ojsc", "cjsc", "pte", "pty", "the",
+    "and", "of",
+];
+
+/// Characters of each name token used to look up candidate list entries
+const NAME_KEY_LENGTH: usize = 4;
+
+/// Matches kept per screening, best first
+pub const MAX_SCREENING_MATCHES: usize = 20;
+
+/// OFAC marks empty CSV fields with this placeholder
+const OFAC_EMPTY: &str = "-0-";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SanctionsSource {
+    OfacSdn,
+    EuConsolidated,
+}
+
+impl SanctionsSource {
+    pub const ALL: [SanctionsSource; 2] = [SanctionsSource::OfacSdn, SanctionsSource::EuConsolidated];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SanctionsSource::OfacSdn => "ofac_sdn",
+            SanctionsSource::EuConsolidated => "eu_consolidated",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SanctionsSource::OfacSdn => "OFAC SDN",
+            SanctionsSource::EuConsolidated => "EU consolidated list",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum ScreeningOutcome {
+    Clear,
+    /// Potential hit: the transaction proceeds, an admin reviews the match
+    Flagged,
+    /// Strong hit: the transaction can't complete until an admin clears the match
+    Blocked,
+}
+
+impl ScreeningOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScreeningOutcome::Clear => "clear",
+            ScreeningOutcome::Flagged => "flagged",
+            ScreeningOutcome::Blocked => "blocked",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ScreeningTrigger {
+    Registration,
+    Transaction,
+    Manual,
+}
+
+impl ScreeningTrigger {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScreeningTrigger::Registration => "registration",
+            ScreeningTrigger::Transaction => "transaction",
+            ScreeningTrigger::Manual => "manual",
+        }
+    }
+}
+
+// ============================================================================
+// THRESHOLDS
+// ============================================================================
+
+#[derive(Debug, Clone)]
+pub struct ScreeningThresholds {
+    /// Name similarity (0-1) from which a list entry is a potential hit
+    pub review_score: f64,
+    /// Name similarity from which a hit blocks transaction completion
+    pub block_score: f64,
+}
+
+impl Default for ScreeningThresholds {
+    fn default() -> Self {
+        Self {
+            review_score: 0.88,
+            block_score: 0.95,
+        }
+    }
+}
+
+impl ScreeningThresholds {
+    /// Defaults, overridable through SANCTIONS_REVIEW_SCORE and SANCTIONS_BLOCK_SCORE
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let env = |name: &str| std::env::var(name).ok().and_then(|v| v.parse::<f64>().ok());
+
+        let review_score = env("SANCTIONS_REVIEW_SCORE").unwrap_or(defaults.review_score).clamp(0.5, 1.0);
+        let block_score = env("SANCTIONS_BLOCK_SCORE").unwrap_or(defaults.block_score).clamp(review_score, 1.0);
+        Self { review_score, block_score }
+    }
+
+    pub fn outcome(&self, top_score: f64) -> ScreeningOutcome {
+        if top_score >= self.block_score {
+            ScreeningOutcome::Blocked
+        } else if top_score >= self.review_score {
+            ScreeningOutcome::Flagged
+        } else {
+            ScreeningOutcome::Clear
+        }
+    }
+}
+
+// ============================================================================
+// DATABASE MODELS
+// ============================================================================
+
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct SanctionsListEntry {
+    pub id: Uuid,
+    pub source: String,
+    pub external_id: String,
+    pub entity_type: String,
+    pub primary_name: String,
+    pub names: Vec<String>,
+    #[serde(skip_serializing)]
+    pub name_keys: Vec<String>,
+    pub programs: Option<String>,
+    pub countries: Vec<String>,
+    pub remarks: Option<String>,
+    pub synced_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct SanctionsListSync {
+    pub id: Uuid,
+    pub source: String,
+    pub status: String,
+    pub entry_count: Option<i32>,
+    pub error_message: Option<String>,
+    pub started_by: Option<Uuid>,
+    pub started_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct SanctionsScreening {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub transaction_id: Option<Uuid>,
+    pub trigger: String,
+    pub screened_names: Vec<String>,
+    pub country_code: Option<String>,
+    pub top_score: f64,
+    pub outcome: String,
+    pub status: String,
+    pub matches: serde_json::Value,
+    pub lists_synced_at: Option<DateTime<Utc>>,
+    pub reviewed_by: Option<Uuid>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub review_notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl SanctionsScreening {
+    pub fn is_pending_review(&self) -> bool {
+        self.status == "pending_review"
+    }
+
+    pub fn parsed_matches(&self) -> Vec<ScreeningMatch> {
+        serde_json::from_value(self.matches.clone()).unwrap_or_default()
+    }
+}
+
+/// A list entry whose name is similar to a screened name
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScreeningMatch {
+    pub entry_id: Uuid,
+    pub source: String,
+    pub external_id: String,
+    pub primary_name: String,
+    /// The entry name (primary or alias) that matched, normalized
+    pub matched_name: String,
+    pub screened_name: String,
+    pub score: f64,
+    pub programs: Option<String>,
+}
+
+// ============================================================================
+// REQUEST / RESPONSE MODELS
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ScreeningReviewDecision {
+    FalsePositive,
+    ConfirmedMatch,
+}
+
+impl ScreeningReviewDecision {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScreeningReviewDecision::FalsePositive => "false_positive",
+            ScreeningReviewDecision::ConfirmedMatch => "confirmed_match",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct ReviewScreeningRequest {
+    pub decision: ScreeningReviewDecision,
+    #[validate(length(min = 1, max = 2000, message = "Notes must be 1-2000 characters"))]
+    pub notes: String,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ScreeningQuery {
+    /// Default: pending_review; `all` for every screening
+    pub status: Option<String>,
+    pub user_id: Option<Uuid>,
+    pub transaction_id: Option<Uuid>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SyncSanctionsListsRequest {
+    /// Omit to sync every configured list
+    pub source: Option<SanctionsSource>,
+}
+
+/// Entry count and latest sync of a sanctions list
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SanctionsListStatus {
+    pub source: SanctionsSource,
+    #[schema(value_type = String)]
+    pub label: &'static str,
+    pub configured: bool,
+    pub entry_count: i64,
+    pub last_sync: Option<SanctionsListSync>,
+}
+
+// ============================================================================
+// NAME MATCHING
+// ============================================================================
+
+/// Lower-case, fold common Latin accents, drop punctuation, legal forms and filler words
+pub fn normalize_name(name: &str) -> String {
+    let mut folded = String::with_capacity(name.len());
+    for c in name.chars().flat_map(char::to_lowercase) {
+        match c {
+            'á' | 'à' | 'â' | 'ä' | 'ã' | 'å' | 'ā' | 'ą' => folded.push('a'),
+            'ç' | 'č' | 'ć' => folded.push('c'),
+            'ď' | 'đ' => folded.push('d'),
+            'é' | 'è' | 'ê' | 'ë' | 'ē' | 'ę' | 'ě' => folded.push('e'),
+            'í' | 'ì' | 'î' | 'ï' | 'ī' => folded.push('i'),
+            'ł' => folded.push('l'),
+            'ñ' | 'ń' | 'ň' => folded.push('n'),
+            'ó' | 'ò' | 'ô' | 'ö' | 'õ' | 'ø' | 'ō' | 'ő' => folded.push('o'),
+            'ř' => folded.push('r'),
+            'š' | 'ś' | 'ş' => folded.push('s'),
+            'ť' | 'ţ' => folded.push('t'),
+            'ú' | 'ù' | 'û' | 'ü' | 'ū' | 'ů' | 'ű' => folded.push('u'),
+            'ý' | 'ÿ' => folded.push('y'),
+            'ž' | 'ź' | 'ż' => folded.push('z'),
+            'ß' => folded.push_str("ss"),
+            'æ' => folded.push_str("ae"),
+            'œ' => folded.push_str("oe"),
+            // Apostrophes join (O'Brien -> obrien); other punctuation separates words
+            '\'' | '’' => {}
+            c if c.is_alphanumeric() => folded.push(c),
+            _ => folded.push(' '),
+        }
+    }
+
+    folded
+        .split_whitespace()
+        .filter(|token| !IGNORED_NAME_TOKENS.contains(token))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Candidate lookup keys of a normalized name: the first characters of each token of
+/// three or more characters (all tokens when the name has no such token)
+pub fn name_keys(normalized: &str) -> Vec<String> {
+    let tokens: Vec<&str> = normalized.split_whitespace().collect();
+    let long: Vec<&str> = tokens.iter().copied().filter(|t| t.chars().count() >= 3).collect();
+    let source = if long.is_empty() { tokens } else { long };
+
+    let mut keys: Vec<String> = source
+        .iter()
+        .map(|t| t.chars().take(NAME_KEY_LENGTH).collect())
+        .collect();
+    keys.sort();
+    keys.dedup();
+    keys
+}
+
+/// Jaro-Winkler similarity of two strings (0-1)
+pub fn jaro_winkler(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+
+    let window = (a.len().max(b.len()) / 2).saturating_sub(1);
+    let mut a_matched = vec![false; a.len()];
+    let mut b_matched = vec![false; b.len()];
+    let mut matches = 0usize;
+
+    for (i, ca) in a.iter().enumerate() {
+        let start = i.saturating_sub(window);
+        let end = (i + window + 1).min(b.len());
+        for j in start..end {
+            if !b_matched[j] && b[j] == *ca {
+                a_matched[i] = true;
+                b_matched[j] = true;
+                matches += 1;
+                break;
+            }
+        }
+    }
+    if matches == 0 {
+        return 0.0;
+    }
+
+    let a_seq = a.iter().zip(&a_matched).filter(|(_, m)| **m).map(|(c, _)| c);
+    let b_seq = b.iter().zip(&b_matched).filter(|(_, m)| **m).map(|(c, _)| c);
+    let transpositions = a_seq.zip(b_seq).filter(|(x, y)| x != y).count() / 2;
+
+    let m = matches as f64;
+    let jaro = (m / a.len() as f64 + m / b.len() as f64 + (m - transpositions as f64) / m) / 3.0;
+
+    let prefix = a.iter().zip(&b).take(4).take_while(|(x, y)| x == y).count();
+    jaro + prefix as f64 * 0.1 * (1.0 - jaro)
+}
+
+/// Similarity of two normalized names (0-1): Jaro-Winkler of the names as written and
+/// with their words sorted, so word order ("Ivanov Ivan" / "Ivan Ivanov") doesn't matter
+pub fn name_similarity(a: &str, b: &str) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    if a == b {
+        return 1.0;
+    }
+
+    let sorted = |s: &str| {
+        let mut tokens: Vec<&str> = s.split_whitespace().collect();
+        tokens.sort_unstable();
+        tokens.join(" ")
+    };
+
+    let score = jaro_winkler(a, b).max(jaro_winkler(&sorted(a), &sorted(b)));
+    (score * 10_000.0).round() / 10_000.0
+}
+
+/// Best match of a normalized screened name against an entry's names
+pub fn best_entry_score(screened: &str, entry_names: &[String]) -> Option<(String, f64)> {
+    entry_names
+        .iter()
+        .map(|name| (name.clone(), name_similarity(screened, name)))
+        .max_by(|x, y| x.1.total_cmp(&y.1))
+}
+
+// ============================================================================
+// LIST PARSING
+// ============================================================================
+
+/// A list entry as downloaded, before normalization
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedSanctionsEntry {
+    pub external_id: String,
+    pub entity_type: &'static str,
+    pub primary_name: String,
+    pub aliases: Vec<String>,
+    pub programs: Option<String>,
+    pub countries: Vec<String>,
+    pub remarks: Option<String>,
+}
+
+impl ParsedSanctionsEntry {
+    /// Normalized primary name and aliases, without duplicates
+    pub fn normalized_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::with_capacity(self.aliases.len() + 1);
+        for name in std::iter::once(&self.primary_name).chain(&self.aliases) {
+            let normalized = normalize_name(name);
+            if !normalized.is_empty() && !names.contains(&normalized) {
+                names.push(normalized);
+            }
+        }
+        names
+    }
+
+    pub fn name_keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self.normalized_names().iter().flat_map(|n| name_keys(n)).collect();
+        keys.sort();
+        keys.dedup();
+        keys
+    }
+}
+
+fn ofac_field(record: &csv::StringRecord, index: usize) -> Option<String> {
+    record
+        .get(index)
+        .map(str::trim)
+        .filter(|v| !v.is_empty() && *v != OFAC_EMPTY)
+        .map(str::to_string)
+}
+
+fn csv_reader(data: &[u8], delimiter: u8, has_headers: bool) -> csv::Reader<&[u8]> {
+    csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(has_headers)
+        .flexible(true)
+        .from_reader(data)
+}
+
+/// Parse the OFAC SDN legacy CSV files: `sdn.csv` (entities), `alt.csv` (aliases) and
+/// `add.csv` (addresses), all headerless and keyed by entity number
+pub fn parse_ofac_sdn(sdn: &[u8], alt: &[u8], add: &[u8]) -> Result<Vec<ParsedSanctionsEntry>, String> {
+    let mut entries: BTreeMap<i64, ParsedSanctionsEntry> = BTreeMap::new();
+
+    for record in csv_reader(sdn, b',', false).records() {
+        let record = record.map_err(|e| format!("Invalid SDN row: {}", e))?;
+        // The file ends with an EOF marker row
+        let Some(ent_num) = record.get(0).and_then(|v| v.trim().parse::<i64>().ok()) else { continue };
+        let Some(name) = ofac_field(&record, 1) else { continue };
+
+        let entity_type = match ofac_field(&record, 2).as_deref() {
+            Some("individual") => "individual",
+            Some("vessel") => "vessel",
+            Some("aircraft") => "aircraft",
+            _ => "entity",
+        };
+
+        entries.insert(ent_num, ParsedSanctionsEntry {
+            external_id: ent_num.to_string(),
+            entity_type,
+            primary_name: name,
+            aliases: Vec::new(),
+            programs: ofac_field(&record, 3),
+            countries: Vec::new(),
+            remarks: ofac_field(&record, 11),
+        });
+    }
+
+    for record in csv_reader(alt, b',', false).records() {
+        let record = record.map_err(|e| format!("Invalid SDN alias row: {}", e))?;
+        let Some(ent_num) = record.get(0).and_then(|v| v.trim().parse::<i64>().ok()) else { continue };
+        if let (Some(entry), Some(alias)) = (entries.get_mut(&ent_num), ofac_field(&record, 3)) {
+            entry.aliases.push(alias);
+        }
+    }
+
+    for record in csv_reader(add, b',', false).records() {
+        let record = record.map_err(|e| format!("Invalid SDN address row: {}", e))?;
+        let Some(ent_num) = record.get(0).and_then(|v| v.trim().parse::<i64>().ok()) else { continue };
+        if let (Some(entry), Some(country)) = (entries.get_mut(&ent_num), ofac_field(&record, 4)) {
+            if !entry.countries.contains(&country) {
+                entry.countries.push(country);
+            }
+        }
+    }
+
+    Ok(entries.into_values().collect())
+}
+
+/// Parse the EU consolidated financial sanctions list CSV (semicolon-separated, one row
+/// per entity, name alias and address combination, grouped by `Entity_LogicalId`)
+pub fn parse_eu_consolidated(data: &[u8]) -> Result<Vec<ParsedSanctionsEntry>, String> {
+    let mut reader = csv_reader(data, b';', true);
+    let headers = reader.headers().map_err(|e| format!("Invalid EU list header: {}", e))?.clone();
+    let column = |name: &str| headers.iter().position(|h| h.trim() == name);
+
+    let id_col = column("Entity_LogicalId").ok_or("EU list has no Entity_LogicalId column")?;
+    let name_col = column("NameAlias_WholeName").ok_or("EU list has no NameAlias_WholeName column")?;
+    let type_col = column("Entity_SubjectType_ClassificationCode");
+    let programme_col = column("Entity_Regulation_Programme");
+    let country_col = column("Address_CountryDescription");
+    let remark_col = column("Entity_Remark");
+
+    let mut entries: BTreeMap<String, ParsedSanctionsEntry> = BTreeMap::new();
+    let field = |record: &csv::StringRecord, col: Option<usize>| {
+        col.and_then(|c| record.get(c))
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+    };
+
+    for record in reader.records() {
+        let record = record.map_err(|e| format!("Invalid EU list row: {}", e))?;
+        let Some(id) = field(&record, Some(id_col)) else { continue };
+        let name = field(&record, Some(name_col));
+
+        let entry = entries.entry(id.clone()).or_insert_with(|| ParsedSanctionsEntry {
+            external_id: id,
+            entity_type: match field(&record, type_col).as_deref() {
+                Some("person") => "individual",
+                Some("enterprise") => "entity",
+                _ => "unknown",
+            },
+            primary_name: String::new(),
+            aliases: Vec::new(),
+            programs: None,
+            countries: Vec::new(),
+            remarks: field(&record, remark_col),
+        });
+
+        if let Some(name) = name {
+            if entry.primary_name.is_empty() {
+                entry.primary_name = name;
+            } else if entry.primary_name != name && !entry.aliases.contains(&name) {
+                entry.aliases.push(name);
+            }
+        }
+        if let Some(programme) = field(&record, programme_col) {
+            match &mut entry.programs {
+                Some(programs) if !programs.split(", ").any(|p| p == programme) => {
+                    programs.push_str(", ");
+                    programs.push_str(&programme);
+                }
+                Some(_) => {}
+                None => entry.programs = Some(programme),
+            }
+        }
+        if let Some(country) = field(&record, country_col) {
+            if !entry.countries.contains(&country) {
+                entry.countries.push(country);
+            }
+        }
+    }
+
+    Ok(entries.into_values().filter(|e| !e.primary_name.is_empty()).collect())
+}
+
+/// Keep the best-scoring match per list entry, best first, at most `MAX_SCREENING_MATCHES`
+pub fn rank_matches(matches: Vec<ScreeningMatch>) -> Vec<ScreeningMatch> {
+    let mut best: HashMap<Uuid, ScreeningMatch> = HashMap::new();
+    for m in matches {
+        match best.get(&m.entry_id) {
+            Some(existing) if existing.score >= m.score => {}
+            _ => {
+                best.insert(m.entry_id, m);
+            }
+        }
+    }
+
+    let mut ranked: Vec<ScreeningMatch> = best.into_values().collect();
+    ranked.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.primary_name.cmp(&b.primary_name)));
+    ranked.truncate(MAX_SCREENING_MATCHES);
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_name() {
+        assert_eq!(normalize_name("Acme Pharma, LLC"), "acme pharma");
+        assert_eq!(normalize_name("  Müller & Söhne GmbH "), "muller sohne");
+        assert_eq!(normalize_name("O'Brien-Smith Trading Co."), "obrien smith trading");
+        assert_eq!(normalize_name("The Limited"), "");
+    }
+
+    #[test]
+    fn test_name_keys() {
+        assert_eq!(name_keys("rosneft oil"), vec!["oil", "rosn"]);
+        assert_eq!(name_keys("al aqsa"), vec!["aqsa"]);
+        assert_eq!(name_keys("ab"), vec!["ab"]);
+    }
+
+    #[test]
+    fn test_jaro_winkler() {
+        assert!((jaro_winkler("martha", "marhta") - 0.9611).abs() < 0.001);
+        assert!((jaro_winkler("dixon", "dicksonx") - 0.8133).abs() < 0.001);
+        assert_eq!(jaro_winkler("abc", "abc"), 1.0);
+        assert_eq!(jaro_winkler("abc", "xyz"), 0.0);
+    }
+
+    #[test]
+    fn test_name_similarity_ignores_word_order() {
+        assert_eq!(name_similarity("ivan ivanov", "ivanov ivan"), 1.0);
+        assert!(name_similarity("rosneft trading", "rosnefft trading") > 0.95);
+        assert!(name_similarity("acme pharma", "global medical supplies") < 0.7);
+        assert_eq!(name_similarity("", "acme"), 0.0);
+    }
+
+    #[test]
+    fn test_thresholds() {
+        let thresholds = ScreeningThresholds::default();
+        assert_eq!(thresholds.outcome(0.5), ScreeningOutcome::Clear);
+        assert_eq!(thresholds.outcome(0.9), ScreeningOutcome::Flagged);
+        assert_eq!(thresholds.outcome(0.95), ScreeningOutcome::Blocked);
+        assert!(ScreeningOutcome::Blocked > ScreeningOutcome::Flagged);
+    }
+
+    #[test]
+    fn test_parse_ofac_sdn() {
+        let sdn = b"36,\"AEROCARIBBEAN AIRLINES\",\"-0- \",\"CUBA\",\"-0- \",\"-0- \",\"-0- \",\"-0- \",\"-0- \",\"-0- \",\"-0- \",\"-0- \"\r\n\
+173,\"ANGLO-CARIBBEAN CO., LTD.\",\"-0- \",\"CUBA\",\"-0- \",\"-0- \",\"-0- \",\"-0- \",\"-0- \",\"-0- \",\"-0- \",\"-0- \"\r\n\
+306,\"PETROV, Ivan\",\"individual\",\"RUSSIA-EO14024\",\"-0- \",\"-0- \",\"-0- \",\"-0- \",\"-0- \",\"-0- \",\"-0- \",\"DOB 01 Jan 1970.\"\r\n\x1a";
+        let alt = b"36,12,\"aka\",\"AERO-CARIBBEAN\",\"-0- \"\r\n306,13,\"aka\",\"PETROV, Ivan Ivanovich\",\"-0- \"\r\n";
+        let add = b"36,25,\"-0- \",\"Havana\",\"Cuba\",\"-0- \"\r\n";
+
+        let entries = parse_ofac_sdn(sdn, alt, add).unwrap();
+        assert_eq!(entries.len(), 3);
+
+        let airline = &entries[0];
+        assert_eq!(airline.external_id, "36");
+        assert_eq!(airline.entity_type, "entity");
+        assert_eq!(airline.aliases, vec!["AERO-CARIBBEAN"]);
+        assert_eq!(airline.countries, vec!["Cuba"]);
+        assert_eq!(airline.programs.as_deref(), Some("CUBA"));
+        assert_eq!(airline.remarks, None);
+
+        let person = &entries[2];
+        assert_eq!(person.entity_type, "individual");
+        assert_eq!(person.normalized_names(), vec!["petrov ivan", "petrov ivan ivanovich"]);
+        assert_eq!(person.remarks.as_deref(), Some("DOB 01 Jan 1970."));
+    }
+
+    #[test]
+    fn test_parse_eu_consolidated() {
+        let csv = "fileGenerationDate;Entity_LogicalId;Entity_Remark;Entity_SubjectType_ClassificationCode;Entity_Regulation_Programme;NameAlias_WholeName;Address_CountryDescription\n\
+2026-10-01;13;;person;RUS;Ivan Petrov;RUSSIAN FEDERATION\n\
+2026-10-01;13;;person;UKR;Иван Петров;RUSSIAN FEDERATION\n\
+2026-10-01;13;;person;RUS;Ivan Petrov;BELARUS\n\
+2026-10-01;27;Front company;enterprise;SYR;Acme Trading LLC;\n";
+
+        let entries = parse_eu_consolidated(csv.as_bytes()).unwrap();
+        assert_eq!(entries.len(), 2);
+
+        let person = &entries[0];
+        assert_eq!(person.external_id, "13");
+        assert_eq!(person.entity_type, "individual");
+        assert_eq!(person.primary_name, "Ivan Petrov");
+        assert_eq!(person.aliases, vec!["Иван Петров"]);
+        assert_eq!(person.programs.as_deref(), Some("RUS, UKR"));
+        assert_eq!(person.countries, vec!["RUSSIAN FEDERATION", "BELARUS"]);
+
+        let company = &entries[1];
+        assert_eq!(company.entity_type, "entity");
+        assert_eq!(company.remarks.as_deref(), Some("Front company"));
+        assert_eq!(company.name_keys(), vec!["acme", "trad"]);
+    }
+
+    #[test]
+    fn test_parse_eu_requires_name_columns() {
+        assert!(parse_eu_consolidated(b"Entity_LogicalId;Other\n1;x\n").is_err());
+    }
+
+    #[test]
+    fn test_rank_matches_keeps_best_per_entry() {
+        let entry = Uuid::new_v4();
+        let other = Uuid::new_v4();
+        let m = |entry_id: Uuid, name: &str, score: f64| ScreeningMatch {
+            entry_id,
+            source: "ofac_sdn".to_string(),
+            external_id: "1".to_string(),
+            primary_name: name.to_string(),
+            matched_name: name.to_string(),
+            screened_name: name.to_string(),
+            score,
+            programs: None,
+        };
+
+        let ranked = rank_matches(vec![m(entry, "a", 0.9), m(other, "b", 0.93), m(entry, "a", 0.97)]);
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(ranked[0].entry_id, entry);
+        assert_eq!(ranked[0].score, 0.97);
+    }
+}
@@ -0,0 +1,412 @@
+use std::collections::BTreeMap;
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+use crate::middleware::error_handling::{AppError, Result};
+use crate::models::ema::EmaSearchRequest;
+use crate::models::inventory::SearchInventoryRequest;
+use crate::models::openfda::OpenFdaSearchRequest;
+
+/// Indexes mirrored from Postgres into the search engine
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchIndex {
+    Marketplace,
+    OpenFda,
+    Ema,
+}
+
+/// What the engine searches, filters, counts and sorts on in an index's documents.
+/// Every document also has a `key` field: the listing id, NDC or EU number.
+#[derive(Debug, Clone, Copy)]
+pub struct IndexSettings {
+    /// In ranking order: matches in earlier fields rank higher
+    pub searchable: &'static [&'static str],
+    pub filterable: &'static [&'static str],
+    pub facets: &'static [&'static str],
+    pub sortable: &'static [&'static str],
+}
+
+impl SearchIndex {
+    pub const ALL: [SearchIndex; 3] = [SearchIndex::Marketplace, SearchIndex::OpenFda, SearchIndex::Ema];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SearchIndex::Marketplace => "marketplace",
+            SearchIndex::OpenFda => "openfda",
+            SearchIndex::Ema => "ema",
+        }
+    }
+
+    pub fn parse(name: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|index| index.as_str() == name.trim().to_lowercase())
+            .ok_or_else(|| AppError::BadRequest(format!(
+                "Unknown search index '{}'. Available: marketplace, openfda, ema",
+                name
+            )))
+    }
+
+    pub fn settings(&self) -> IndexSettings {
+        match self {
+            SearchIndex::Marketplace => IndexSettings {
+                searchable: &[
+                    "brand_name", "generic_name", "manufacturer", "ndc_code", "category",
+                    "seller_name", "description",
+                ],
+                filterable: &[
+                    "pharmaceutical_id", "ndc_code", "product_domain", "quantity", "unit_price",
+                    "expiry_date_ts",
+                ],
+                facets: &[
+                    "manufacturer", "category", "dosage_form", "product_domain", "seller_verified",
+                    "seller_country",
+                ],
+                sortable: &["expiry_date_ts", "unit_price", "quantity", "created_at_ts", "updated_at_ts"],
+            },
+            SearchIndex::OpenFda => IndexSettings {
+                searchable: &["brand_name", "generic_name", "product_ndc", "substances", "labeler_name", "pharm_class"],
+                filterable: &[],
+                facets: &["labeler_name", "dosage_form", "product_type", "marketing_category", "route", "dea_schedule"],
+                sortable: &["brand_name"],
+            },
+            SearchIndex::Ema => IndexSettings {
+                searchable: &[
+                    "product_name", "inn_name", "eu_number", "mah_name", "active_substances", "atc_code",
+                    "therapeutic_indication",
+                ],
+                filterable: &["language_code", "authorization_status"],
+                facets: &[
+                    "authorization_status", "therapeutic_area", "pharmaceutical_form", "language_code",
+                    "orphan_designation",
+                ],
+                sortable: &["product_name"],
+            },
+        }
+    }
+
+    /// Validate a comma-separated `facets` parameter against the index
+    pub fn parse_facets(&self, requested: Option<&str>) -> Result<Vec<&'static str>> {
+        let available = self.settings().facets;
+        let mut facets = Vec::new();
+
+        for name in requested.unwrap_or_default().split(',').map(str::trim).filter(|f| !f.is_empty()) {
+            let facet = available.iter().find(|f| **f == name).ok_or_else(|| {
+                AppError::BadRequest(format!("Unknown facet '{}'. Available: {}", name, available.join(", ")))
+            })?;
+            if !facets.contains(facet) {
+                facets.push(*facet);
+            }
+        }
+
+        Ok(facets)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SearchFilter {
+    Equals(&'static str, Value),
+    AtLeast(&'static str, Value),
+    AtMost(&'static str, Value),
+}
+
+/// An engine-independent search; `SearchEngine` implementations translate it
+#[derive(Debug, Clone, Default)]
+pub struct SearchQuery {
+    /// Typo-tolerant free text; empty matches every document
+    pub text: String,
+    /// Fields the text is matched against; all searchable fields when empty
+    pub fields: Vec<&'static str>,
+    /// All must match
+    pub filters: Vec<SearchFilter>,
+    /// Fields whose value counts are returned with the hits
+    pub facets: Vec<&'static str>,
+    /// Relevance order when None
+    pub sort: Option<(&'static str, SortOrder)>,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+/// Keys of the matching documents in ranking order
+#[derive(Debug, Clone, Default)]
+pub struct SearchHits {
+    pub keys: Vec<String>,
+    /// Engine estimate of all matches, beyond the page
+    pub total: u64,
+    pub facets: BTreeMap<String, BTreeMap<String, u64>>,
+}
+
+/// Search results with facet counts, returned when a search asks for `facets`
+#[derive(Debug, Clone, Serialize)]
+pub struct FacetedSearchResponse<T> {
+    pub results: Vec<T>,
+    /// Facet field -> value -> matching documents; when Postgres answered, only filled
+    /// for browsing the whole marketplace (from a periodically refreshed summary)
+    pub facets: BTreeMap<String, BTreeMap<String, u64>>,
+    /// When the summary the facets came from was refreshed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub facets_as_of: Option<DateTime<Utc>>,
+    /// Estimated matches in total; only known when the search engine answered
+    pub total: Option<u64>,
+    /// Search engine name, or "postgres" for the fallback
+    pub engine: String,
+}
+
+impl<T> FacetedSearchResponse<T> {
+    pub fn from_postgres(results: Vec<T>) -> Self {
+        Self { results, facets: BTreeMap::new(), facets_as_of: None, total: None, engine: "postgres".to_string() }
+    }
+}
+
+impl<T: Serialize> FacetedSearchResponse<T> {
+    /// Response body: the bare results array unless the search asked for facets, so
+    /// existing clients see no change
+    pub fn into_body(self, with_facets: bool) -> Result<Value> {
+        let body = if with_facets {
+            serde_json::to_value(&self)
+        } else {
+            serde_json::to_value(&self.results)
+        };
+        body.map_err(|e| AppError::Internal(e.into()))
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SearchIndexStatus {
+    pub index: SearchIndex,
+    /// Changes are queued for the engine
+    pub enabled: bool,
+    /// When the index was first fully built; searches use Postgres until then
+    pub ready_at: Option<DateTime<Utc>>,
+    pub reindex_requested_at: Option<DateTime<Utc>>,
+    pub queued: i64,
+    pub oldest_queued_at: Option<DateTime<Utc>>,
+    /// Documents in the engine; None when it couldn't be reached
+    pub documents: Option<u64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SearchEngineStatus {
+    /// None without SEARCH_ENGINE; every search then uses Postgres
+    pub engine: Option<String>,
+    pub indexes: Vec<SearchIndexStatus>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ReindexRequest {
+    /// All indexes when omitted
+    pub index: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReindexResponse {
+    pub indexes: Vec<SearchIndex>,
+    pub queued: i64,
+}
+
+// ============================================================================
+// Request translation: None when a search has to run on Postgres, either
+// because it has neither text nor facets (Postgres answers those exactly) or
+// because it uses a filter the index can't express.
+// ============================================================================
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty())
+}
+
+fn date_ts(date: NaiveDate) -> Value {
+    Value::from(date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc().timestamp())
+}
+
+fn price(value: rust_decimal::Decimal) -> Value {
+    Value::from(value.to_f64().unwrap_or_default())
+}
+
+impl SearchInventoryRequest {
+    pub fn engine_query(&self) -> Result<Option<SearchQuery>> {
+        let facets = SearchIndex::Marketplace.parse_facets(self.facets.as_deref())?;
+
+        let q = non_empty(&self.q);
+        let field_terms = [
+            ("brand_name", non_empty(&self.brand_name)),
+            ("generic_name", non_empty(&self.generic_name)),
+            ("manufacturer", non_empty(&self.manufacturer)),
+        ];
+        let has_text = q.is_some() || field_terms.iter().any(|(_, term)| term.is_some());
+
+        let sort = match self.sort_by.as_deref() {
+            None => None,
+            Some(sort_by) => {
+                let field = match sort_by {
+                    "expiry_date" => "expiry_date_ts",
+                    "unit_price" => "unit_price",
+                    "quantity" => "quantity",
+                    "created_at" => "created_at_ts",
+                    "updated_at" => "updated_at_ts",
+                    _ => return Ok(None),
+                };
+                let order = match self.sort_order.as_deref() {
+                    Some(order) if order.eq_ignore_ascii_case("desc") => SortOrder::Desc,
+                    _ => SortOrder::Asc,
+                };
+                Some((field, order))
+            }
+        };
+
+        let only_available = matches!(self.status.as_deref(), None | Some("available"));
+        if (!has_text && facets.is_empty()) || self.free_of.is_some() || !only_available {
+            return Ok(None);
+        }
+
+        // Free text searches every field; field terms only their own
+        let (text, fields) = match q {
+            Some(q) => {
+                let terms: Vec<&str> = std::iter::once(q).chain(field_terms.iter().filter_map(|(_, t)| *t)).collect();
+                (terms.join(" "), Vec::new())
+            }
+            None => {
+                let present: Vec<(&'static str, &str)> =
+                    field_terms.iter().filter_map(|(field, term)| term.map(|t| (*field, t))).collect();
+                (
+                    present.iter().map(|(_, t)| *t).collect::<Vec<_>>().join(" "),
+                    present.iter().map(|(f, _)| *f).collect(),
+                )
+            }
+        };
+
+        let mut filters = Vec::new();
+        if let Some(pharmaceutical_id) = self.pharmaceutical_id {
+            filters.push(SearchFilter::Equals("pharmaceutical_id", Value::from(pharmaceutical_id.to_string())));
+        }
+        if let Some(ndc_code) = non_empty(&self.ndc_code) {
+            filters.push(SearchFilter::Equals("ndc_code", Value::from(ndc_code)));
+        }
+        if let Some(product_domain) = self.product_domain {
+            filters.push(SearchFilter::Equals("product_domain", Value::from(product_domain.as_str())));
+        }
+        if let Some(expiry_after) = self.expiry_after {
+            filters.push(SearchFilter::AtLeast("expiry_date_ts", date_ts(expiry_after)));
+        }
+        if let Some(expiry_before) = self.expiry_before {
+            filters.push(SearchFilter::AtMost("expiry_date_ts", date_ts(expiry_before)));
+        }
+        if let Some(min_quantity) = self.min_quantity {
+            filters.push(SearchFilter::AtLeast("quantity", Value::from(min_quantity)));
+        }
+        if let Some(max_quantity) = self.max_quantity {
+            filters.push(SearchFilter::AtMost("quantity", Value::from(max_quantity)));
+        }
+        if let Some(min_price) = self.min_price {
+            filters.push(SearchFilter::AtLeast("unit_price", price(min_price)));
+        }
+        if let Some(max_price) = self.max_price {
+            filters.push(SearchFilter::AtMost("unit_price", price(max_price)));
+        }
+
+        Ok(Some(SearchQuery {
+            text,
+            fields,
+            filters,
+            facets,
+            // Without text, keep the Postgres default order
+            sort: sort.or((!has_text).then_some(("expiry_date_ts", SortOrder::Asc))),
+            limit: self.limit.unwrap_or(50).min(100),
+            offset: self.offset.unwrap_or(0),
+        }))
+    }
+}
+
+impl OpenFdaSearchRequest {
+    pub fn engine_query(&self) -> Result<Option<SearchQuery>> {
+        let facets = SearchIndex::OpenFda.parse_facets(self.facets.as_deref())?;
+        let text = non_empty(&self.query);
+        if text.is_none() && facets.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(SearchQuery {
+            text: text.unwrap_or_default().to_string(),
+            facets,
+            sort: text.is_none().then_some(("brand_name", SortOrder::Asc)),
+            limit: self.limit.unwrap_or(20).min(100),
+            offset: self.offset.unwrap_or(0),
+            ..Default::default()
+        }))
+    }
+}
+
+impl EmaSearchRequest {
+    pub fn engine_query(&self) -> Result<Option<SearchQuery>> {
+        let facets = SearchIndex::Ema.parse_facets(self.facets.as_deref())?;
+        let text = non_empty(&self.query);
+
+        // Partial-match filters stay on Postgres
+        let partial_filters = self.therapeutic_area.is_some() || self.atc_code.is_some() || self.mah_name.is_some();
+        if (text.is_none() && facets.is_empty()) || partial_filters {
+            return Ok(None);
+        }
+
+        let mut filters = Vec::new();
+        if let Some(language) = non_empty(&self.language) {
+            filters.push(SearchFilter::Equals("language_code", Value::from(language)));
+        }
+        if let Some(status) = non_empty(&self.authorization_status) {
+            filters.push(SearchFilter::Equals("authorization_status", Value::from(status)));
+        }
+
+        Ok(Some(SearchQuery {
+            text: text.unwrap_or_default().to_string(),
+            filters,
+            facets,
+            sort: text.is_none().then_some(("product_name", SortOrder::Asc)),
+            limit: self.limit.unwrap_or(20).min(100),
+            offset: self.offset.unwrap_or(0),
+            ..Default::default()
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn marketplace_request(query: &str) -> SearchInventoryRequest {
+        serde_json::from_value(serde_json::from_str(query).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_marketplace_routing() {
+        // Plain filtered listings stay on Postgres
+        assert!(marketplace_request(r#"{"min_quantity": 5}"#).engine_query().unwrap().is_none());
+        assert!(marketplace_request(r#"{"q": "amoxicilin", "free_of": "gelatin-free"}"#).engine_query().unwrap().is_none());
+        assert!(marketplace_request(r#"{"q": "amoxicilin", "sort_by": "batch_number"}"#).engine_query().unwrap().is_none());
+
+        let query = marketplace_request(r#"{"brand_name": "amoxil", "min_quantity": 5, "sort_by": "unit_price", "sort_order": "desc"}"#)
+            .engine_query()
+            .unwrap()
+            .unwrap();
+        assert_eq!(query.text, "amoxil");
+        assert_eq!(query.fields, vec!["brand_name"]);
+        assert_eq!(query.filters, vec![SearchFilter::AtLeast("quantity", Value::from(5))]);
+        assert_eq!(query.sort, Some(("unit_price", SortOrder::Desc)));
+
+        // Facets alone go to the engine, in the Postgres default order
+        let query = marketplace_request(r#"{"facets": "manufacturer,category,manufacturer"}"#)
+            .engine_query()
+            .unwrap()
+            .unwrap();
+        assert_eq!(query.facets, vec!["manufacturer", "category"]);
+        assert_eq!(query.sort, Some(("expiry_date_ts", SortOrder::Asc)));
+
+        assert!(marketplace_request(r#"{"facets": "batch_number"}"#).engine_query().is_err());
+    }
+}
@@ -0,0 +1,295 @@
+use std::collections::HashMap;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use sqlx::FromRow;
+use uuid::Uuid;
+use crate::middleware::error_handling::{AppError, Result};
+
+/// Record types covered by semantic search, in the order they are listed
+pub const SEMANTIC_ENTITY_TYPES: [&str; 4] = ["pharmaceutical", "inventory", "openfda", "ema"];
+
+/// Share of the blended score taken by vector similarity; the rest is full-text rank
+pub const DEFAULT_SEMANTIC_WEIGHT: f64 = 0.6;
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SemanticSearchRequest {
+    pub q: Option<String>,
+    /// Comma-separated record types (default: all)
+    pub types: Option<String>,
+    /// 0.0 = full-text only, 1.0 = vector similarity only
+    pub semantic_weight: Option<f64>,
+    pub limit: Option<i64>,
+}
+
+impl SemanticSearchRequest {
+    pub fn query_text(&self) -> Option<&str> {
+        self.q.as_deref().map(str::trim).filter(|q| !q.is_empty())
+    }
+
+    pub fn weight(&self) -> f64 {
+        self.semantic_weight
+            .filter(|w| w.is_finite())
+            .unwrap_or(DEFAULT_SEMANTIC_WEIGHT)
+            .clamp(0.0, 1.0)
+    }
+
+    /// Requested record types, validated and deduplicated in `SEMANTIC_ENTITY_TYPES` order
+    pub fn type_list(&self) -> Result<Vec<String>> {
+        parse_entity_types(self.types.as_deref())
+    }
+}
+
+/// Comma-separated record types, `None` or empty meaning all of them
+pub fn parse_entity_types(types: Option<&str>) -> Result<Vec<String>> {
+    let requested: Vec<String> = match types.map(str::trim).filter(|t| !t.is_empty()) {
+        None => return Ok(SEMANTIC_ENTITY_TYPES.iter().map(|t| t.to_string()).collect()),
+        Some(list) => list.split(',').map(|t| t.trim().to_lowercase()).collect(),
+    };
+
+    if let Some(unknown) = requested.iter().find(|t| !SEMANTIC_ENTITY_TYPES.contains(&t.as_str())) {
+        return Err(AppError::BadRequest(format!(
+            "Unknown record type '{}'. Available: {}",
+            unknown,
+            SEMANTIC_ENTITY_TYPES.join(", ")
+        )));
+    }
+
+    Ok(SEMANTIC_ENTITY_TYPES
+        .iter()
+        .filter(|t| requested.iter().any(|r| r == *t))
+        .map(|t| t.to_string())
+        .collect())
+}
+
+/// A record as it is embedded: display fields plus the text the embedding is built from
+#[derive(Debug, Clone, FromRow)]
+pub struct SearchDocument {
+    pub entity_id: Uuid,
+    pub title: String,
+    pub subtitle: Option<String>,
+    pub content: String,
+}
+
+impl SearchDocument {
+    pub fn content_hash(&self) -> String {
+        use sha2::{Digest, Sha256};
+        hex::encode(Sha256::digest(self.content.as_bytes()))
+    }
+}
+
+/// A match from either ranking: vector similarity (0..1) or full-text `ts_rank`
+#[derive(Debug, Clone, FromRow)]
+pub struct SearchCandidate {
+    pub entity_type: String,
+    pub entity_id: Uuid,
+    pub title: String,
+    pub subtitle: Option<String>,
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SemanticSearchResult {
+    pub entity_type: String,
+    pub entity_id: Uuid,
+    pub title: String,
+    pub subtitle: Option<String>,
+    pub score: f64,
+    pub similarity: Option<f64>,
+    pub text_rank: Option<f64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SemanticSearchResponse {
+    pub query: String,
+    pub types: Vec<String>,
+    pub semantic_weight: f64,
+    pub results: Vec<SemanticSearchResult>,
+}
+
+/// Merge vector and full-text candidates into one ranking. Text ranks are normalized by
+/// the best rank of the query so both scores share a 0..1 scale; a record missing from
+/// one ranking scores 0 there.
+pub fn blend_candidates(
+    vector: Vec<SearchCandidate>,
+    text: Vec<SearchCandidate>,
+    semantic_weight: f64,
+    limit: usize,
+) -> Vec<SemanticSearchResult> {
+    let max_rank = text.iter().map(|c| c.score).fold(0.0, f64::max);
+    let mut merged: HashMap<(String, Uuid), SemanticSearchResult> = HashMap::new();
+
+    for candidate in vector {
+        merged.insert(
+            (candidate.entity_type.clone(), candidate.entity_id),
+            SemanticSearchResult {
+                entity_type: candidate.entity_type,
+                entity_id: candidate.entity_id,
+                title: candidate.title,
+                subtitle: candidate.subtitle,
+                score: 0.0,
+                similarity: Some(candidate.score.clamp(0.0, 1.0)),
+                text_rank: None,
+            },
+        );
+    }
+
+    for candidate in text {
+        let normalized = if max_rank > 0.0 { candidate.score / max_rank } else { 0.0 };
+        merged
+            .entry((candidate.entity_type.clone(), candidate.entity_id))
+            .and_modify(|r| {
+                // Current display fields win over the ones stored at embedding time
+                r.title = candidate.title.clone();
+                r.subtitle = candidate.subtitle.clone();
+                r.text_rank = Some(normalized);
+            })
+            .or_insert_with(|| SemanticSearchResult {
+                entity_type: candidate.entity_type,
+                entity_id: candidate.entity_id,
+                title: candidate.title,
+                subtitle: candidate.subtitle,
+                score: 0.0,
+                similarity: None,
+                text_rank: Some(normalized),
+            });
+    }
+
+    let mut results: Vec<SemanticSearchResult> = merged
+        .into_values()
+        .map(|mut r| {
+            r.score = semantic_weight * r.similarity.unwrap_or(0.0)
+                + (1.0 - semantic_weight) * r.text_rank.unwrap_or(0.0);
+            r
+        })
+        .collect();
+
+    results.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.title.to_lowercase().cmp(&b.title.to_lowercase()))
+            .then_with(|| a.entity_id.cmp(&b.entity_id))
+    });
+    results.truncate(limit);
+    results
+}
+
+// ============================================================================
+// Backfill Jobs
+// ============================================================================
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct StartEmbeddingBackfillRequest {
+    /// Record types to embed (default: all)
+    pub entity_types: Option<Vec<String>>,
+    /// Re-embed every record instead of only new and changed ones
+    #[serde(default)]
+    pub full_rebuild: bool,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct EmbeddingBackfillJob {
+    pub id: Uuid,
+    pub entity_types: Vec<String>,
+    pub full_rebuild: bool,
+    pub status: String,
+    pub triggered_by: String,
+    pub records_scanned: i32,
+    pub records_embedded: i32,
+    pub records_removed: i32,
+    pub error_message: Option<String>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// IVFFlat list count for a table size: rows / 1000 up to 1M rows, sqrt(rows) beyond,
+/// as the pgvector documentation recommends
+pub fn ivfflat_lists(rows: i64) -> i64 {
+    let lists = if rows <= 1_000_000 { rows / 1000 } else { (rows as f64).sqrt() as i64 };
+    lists.max(10)
+}
+
+/// Rebuilding is only worth it once the ideal list count has drifted by 2x either way
+pub fn ivfflat_needs_rebuild(current_lists: Option<i64>, target_lists: i64) -> bool {
+    match current_lists {
+        None => true,
+        Some(current) => target_lists >= current * 2 || target_lists * 2 <= current,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(entity_type: &str, id: Uuid, title: &str, score: f64) -> SearchCandidate {
+        SearchCandidate {
+            entity_type: entity_type.to_string(),
+            entity_id: id,
+            title: title.to_string(),
+            subtitle: None,
+            score,
+        }
+    }
+
+    #[test]
+    fn test_blend_candidates() {
+        let (both, vector_only, text_only) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+
+        let results = blend_candidates(
+            vec![
+                candidate("pharmaceutical", both, "Metformin", 0.8),
+                candidate("openfda", vector_only, "Glucophage", 0.9),
+            ],
+            vec![
+                candidate("pharmaceutical", both, "Metformin HCl", 0.4),
+                candidate("ema", text_only, "Metformin Teva", 0.2),
+            ],
+            0.5,
+            10,
+        );
+
+        assert_eq!(results.len(), 3);
+        // 0.5 * 0.8 + 0.5 * 1.0
+        assert_eq!(results[0].entity_id, both);
+        assert!((results[0].score - 0.9).abs() < 1e-9);
+        assert_eq!(results[0].title, "Metformin HCl");
+        assert_eq!(results[1].entity_id, vector_only);
+        assert_eq!(results[2].entity_id, text_only);
+        assert_eq!(results[2].text_rank, Some(0.5));
+    }
+
+    #[test]
+    fn test_blend_keeps_types_apart_and_truncates() {
+        let id = Uuid::new_v4();
+        let results = blend_candidates(
+            vec![candidate("pharmaceutical", id, "A", 0.5), candidate("inventory", id, "B", 0.4)],
+            Vec::new(),
+            1.0,
+            1,
+        );
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].entity_type, "pharmaceutical");
+    }
+
+    #[test]
+    fn test_parse_entity_types() {
+        assert_eq!(parse_entity_types(None).unwrap().len(), SEMANTIC_ENTITY_TYPES.len());
+        assert_eq!(parse_entity_types(Some("EMA, inventory,ema")).unwrap(), vec!["inventory", "ema"]);
+        assert!(parse_entity_types(Some("recalls")).is_err());
+    }
+
+    #[test]
+    fn test_ivfflat_lists() {
+        assert_eq!(ivfflat_lists(0), 10);
+        assert_eq!(ivfflat_lists(250_000), 250);
+        assert_eq!(ivfflat_lists(4_000_000), 2000);
+        assert!(ivfflat_needs_rebuild(None, 10));
+        assert!(!ivfflat_needs_rebuild(Some(100), 150));
+        assert!(ivfflat_needs_rebuild(Some(100), 10));
+        assert!(ivfflat_needs_rebuild(Some(100), 250));
+    }
+}
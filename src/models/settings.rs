@@ -0,0 +1,341 @@
+/// Runtime settings: the known keys, their types, bounds and defaults
+///
+/// A setting's default is its environment variable when set and valid, otherwise the
+/// built-in value; a row in `settings` overrides it until deleted.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Postgres NOTIFY channel the settings trigger publishes changed keys on
+pub const SETTINGS_CHANNEL: &str = "atlas_settings";
+
+pub const RATE_LIMIT_AUTH_MAX_REQUESTS: &str = "rate_limit.auth.max_requests";
+pub const RATE_LIMIT_AUTH_WINDOW_SECONDS: &str = "rate_limit.auth.window_seconds";
+pub const RATE_LIMIT_API_MAX_REQUESTS: &str = "rate_limit.api.max_requests";
+pub const RATE_LIMIT_API_WINDOW_SECONDS: &str = "rate_limit.api.window_seconds";
+pub const ERP_SYNC_POLL_MINUTES: &str = "erp_sync.poll_minutes";
+pub const ERP_SYNC_MAX_CONCURRENT: &str = "erp_sync.max_concurrent";
+pub const AI_QUOTA_MAX_TOPUP_AMOUNT: &str = "ai_quota.max_topup_amount";
+pub const CORS_ALLOWED_ORIGINS: &str = "cors.allowed_origins";
+pub const MAINTENANCE_ENABLED: &str = "maintenance.enabled";
+pub const MAINTENANCE_RETRY_AFTER_SECONDS: &str = "maintenance.retry_after_seconds";
+pub const MAINTENANCE_MESSAGE: &str = "maintenance.message";
+pub const VERIFICATION_AUTO_REJECT_DAYS: &str = "verification.auto_reject_days";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingKind {
+    /// Whole number within the bounds, inclusive
+    Integer { min: i64, max: i64 },
+    /// Non-empty list of `scheme://host[:port]` origins
+    Origins,
+    /// true or false (`true`/`false`, `1`/`0`, `yes`/`no`, `on`/`off` in the environment)
+    Boolean,
+    /// Non-empty text up to `max_len` characters
+    Text { max_len: usize },
+}
+
+#[derive(Debug)]
+pub struct SettingDefinition {
+    pub key: &'static str,
+    pub description: &'static str,
+    pub kind: SettingKind,
+    /// Environment variable the default is read from
+    pub env: Option<&'static str>,
+    /// Built-in default, in the environment variable's format
+    pub default: &'static str,
+}
+
+pub const SETTING_DEFINITIONS: &[SettingDefinition] = &[
+    SettingDefinition {
+        key: RATE_LIMIT_AUTH_MAX_REQUESTS,
+        description: "Requests per IP and window on the public auth endpoints (login, register, refresh)",
+        kind: SettingKind::Integer { min: 1, max: 1_000 },
+        env: None,
+        default: "5",
+    },
+    SettingDefinition {
+        key: RATE_LIMIT_AUTH_WINDOW_SECONDS,
+        description: "Window of the auth endpoint rate limit, in seconds",
+        kind: SettingKind::Integer { min: 1, max: 86_400 },
+        env: None,
+        default: "900",
+    },
+    SettingDefinition {
+        key: RATE_LIMIT_API_MAX_REQUESTS,
+        description: "Requests per IP and window across the API",
+        kind: SettingKind::Integer { min: 1, max: 100_000 },
+        env: None,
+        default: "100",
+    },
+    SettingDefinition {
+        key: RATE_LIMIT_API_WINDOW_SECONDS,
+        description: "Window of the API rate limit, in seconds",
+        kind: SettingKind::Integer { min: 1, max: 86_400 },
+        env: None,
+        default: "60",
+    },
+    SettingDefinition {
+        key: ERP_SYNC_POLL_MINUTES,
+        description: "How often the ERP sync scheduler looks for due connections, in minutes",
+        kind: SettingKind::Integer { min: 1, max: 1_440 },
+        env: Some("ERP_SYNC_POLL_MINUTES"),
+        default: "1",
+    },
+    SettingDefinition {
+        key: ERP_SYNC_MAX_CONCURRENT,
+        description: "ERP connections synced in parallel",
+        kind: SettingKind::Integer { min: 1, max: 64 },
+        env: Some("ERP_SYNC_MAX_CONCURRENT"),
+        default: "4",
+    },
+    SettingDefinition {
+        key: AI_QUOTA_MAX_TOPUP_AMOUNT,
+        description: "Largest single AI quota top-up, in requests or dollars",
+        kind: SettingKind::Integer { min: 1, max: 1_000_000 },
+        env: None,
+        default: "10000",
+    },
+    SettingDefinition {
+        key: CORS_ALLOWED_ORIGINS,
+        description: "Origins allowed to call the API from a browser",
+        kind: SettingKind::Origins,
+        env: Some("CORS_ORIGINS"),
+        default: "http://localhost:3000",
+    },
+    SettingDefinition {
+        key: MAINTENANCE_ENABLED,
+        description: "Maintenance mode: non-admin requests get 503 and schedulers pause",
+        kind: SettingKind::Boolean,
+        env: Some("MAINTENANCE_MODE"),
+        default: "false",
+    },
+    SettingDefinition {
+        key: MAINTENANCE_RETRY_AFTER_SECONDS,
+        description: "Retry-After sent with maintenance responses, in seconds",
+        kind: SettingKind::Integer { min: 1, max: 86_400 },
+        env: Some("MAINTENANCE_RETRY_AFTER_SECONDS"),
+        default: "300",
+    },
+    SettingDefinition {
+        key: MAINTENANCE_MESSAGE,
+        description: "Message returned with maintenance responses",
+        kind: SettingKind::Text { max_len: 500 },
+        env: Some("MAINTENANCE_MESSAGE"),
+        default: "Atlas Pharma is down for scheduled maintenance. Please try again shortly.",
+    },
+    SettingDefinition {
+        key: VERIFICATION_AUTO_REJECT_DAYS,
+        description: "Pending verification applications without activity for this many days are rejected; 0 disables",
+        kind: SettingKind::Integer { min: 0, max: 365 },
+        env: Some("VERIFICATION_AUTO_REJECT_DAYS"),
+        default: "30",
+    },
+];
+
+impl SettingDefinition {
+    pub fn find(key: &str) -> Option<&'static SettingDefinition> {
+        SETTING_DEFINITIONS.iter().find(|definition| definition.key == key)
+    }
+
+    /// The environment variable if set and valid, otherwise the built-in default
+    pub fn default_value(&self) -> Value {
+        self.env
+            .and_then(|env| std::env::var(env).ok())
+            .and_then(|raw| self.parse_raw(&raw))
+            .or_else(|| self.parse_raw(self.default))
+            .unwrap_or(Value::Null)
+    }
+
+    /// Parse a value in environment variable format (`5`, `a,b`, `true`)
+    pub fn parse_raw(&self, raw: &str) -> Option<Value> {
+        let value = match self.kind {
+            SettingKind::Integer { .. } => Value::from(raw.trim().parse::<i64>().ok()?),
+            SettingKind::Boolean => match raw.trim().to_lowercase().as_str() {
+                "true" | "1" | "yes" | "on" => Value::Bool(true),
+                "false" | "0" | "no" | "off" => Value::Bool(false),
+                _ => return None,
+            },
+            SettingKind::Text { .. } => Value::from(raw),
+            SettingKind::Origins => Value::from(
+                raw.split(',')
+                    .map(str::trim)
+                    .filter(|origin| !origin.is_empty())
+                    .collect::<Vec<_>>(),
+            ),
+        };
+        self.validate(&value).ok()
+    }
+
+    /// Check `value` against the setting's type and bounds; returns it normalized
+    pub fn validate(&self, value: &Value) -> Result<Value, String> {
+        match self.kind {
+            SettingKind::Integer { min, max } => {
+                let number = value
+                    .as_i64()
+                    .ok_or_else(|| format!("{} must be a whole number", self.key))?;
+                if number < min || number > max {
+                    return Err(format!("{} must be between {} and {}", self.key, min, max));
+                }
+                Ok(Value::from(number))
+            }
+            SettingKind::Origins => {
+                let origins = value
+                    .as_array()
+                    .ok_or_else(|| format!("{} must be a list of origins", self.key))?;
+                if origins.is_empty() {
+                    return Err(format!("{} must list at least one origin", self.key));
+                }
+                let mut normalized = Vec::with_capacity(origins.len());
+                for origin in origins {
+                    let origin = origin
+                        .as_str()
+                        .map(|origin| origin.trim().trim_end_matches('/'))
+                        .ok_or_else(|| format!("{} must be a list of origins", self.key))?;
+                    validate_origin(origin)?;
+                    normalized.push(origin.to_string());
+                }
+                Ok(Value::from(normalized))
+            }
+            SettingKind::Boolean => value
+                .as_bool()
+                .map(Value::Bool)
+                .ok_or_else(|| format!("{} must be true or false", self.key)),
+            SettingKind::Text { max_len } => {
+                let text = value
+                    .as_str()
+                    .map(str::trim)
+                    .ok_or_else(|| format!("{} must be text", self.key))?;
+                if text.is_empty() {
+                    return Err(format!("{} must not be empty", self.key));
+                }
+                if text.chars().count() > max_len {
+                    return Err(format!("{} must be at most {} characters", self.key, max_len));
+                }
+                Ok(Value::from(text))
+            }
+        }
+    }
+}
+
+/// `scheme://host[:port]` with an http(s) scheme; wildcards are refused because
+/// credentials (the auth cookie) are allowed cross-origin
+fn validate_origin(origin: &str) -> Result<(), String> {
+    let host = origin
+        .strip_prefix("https://")
+        .or_else(|| origin.strip_prefix("http://"))
+        .ok_or_else(|| format!("Origin '{}' must start with http:// or https://", origin))?;
+    if host.is_empty() || host.contains(['/', '*', ' ', '?', '#']) {
+        return Err(format!("Origin '{}' must be scheme://host[:port] without wildcards or a path", origin));
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Database Models
+// ============================================================================
+
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct SettingOverride {
+    pub key: String,
+    pub value: Value,
+    pub updated_by: Option<Uuid>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct SettingChange {
+    pub id: Uuid,
+    pub key: String,
+    pub old_value: Option<Value>,
+    /// None when reset to the default
+    pub new_value: Option<Value>,
+    pub changed_by: Option<Uuid>,
+    pub changed_at: DateTime<Utc>,
+}
+
+// ============================================================================
+// API Models
+// ============================================================================
+
+/// A setting with its effective value and where that comes from
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SettingView {
+    #[schema(value_type = String)]
+    pub key: &'static str,
+    #[schema(value_type = String)]
+    pub description: &'static str,
+    pub value: Value,
+    pub default_value: Value,
+    /// Whether `value` is a runtime override rather than the default
+    pub overridden: bool,
+    pub updated_by: Option<Uuid>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl SettingView {
+    pub fn new(definition: &'static SettingDefinition, row: Option<&SettingOverride>) -> Self {
+        let default_value = definition.default_value();
+        Self {
+            key: definition.key,
+            description: definition.description,
+            value: row.map(|row| row.value.clone()).unwrap_or_else(|| default_value.clone()),
+            default_value,
+            overridden: row.is_some(),
+            updated_by: row.and_then(|row| row.updated_by),
+            updated_at: row.map(|row| row.updated_at),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateSettingRequest {
+    pub value: Value,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_integer_bounds() {
+        let definition = SettingDefinition::find(ERP_SYNC_POLL_MINUTES).unwrap();
+        assert_eq!(definition.validate(&json!(15)), Ok(json!(15)));
+        assert!(definition.validate(&json!(0)).is_err());
+        assert!(definition.validate(&json!(1_441)).is_err());
+        assert!(definition.validate(&json!("15")).is_err());
+        assert!(definition.validate(&json!(1.5)).is_err());
+    }
+
+    #[test]
+    fn test_origin_validation() {
+        let definition = SettingDefinition::find(CORS_ALLOWED_ORIGINS).unwrap();
+        assert_eq!(
+            definition.validate(&json!(["https://app.atlaspharma.com/", "http://localhost:3000"])),
+            Ok(json!(["https://app.atlaspharma.com", "http://localhost:3000"]))
+        );
+        assert!(definition.validate(&json!([])).is_err());
+        assert!(definition.validate(&json!(["*"])).is_err());
+        assert!(definition.validate(&json!(["https://*.atlaspharma.com"])).is_err());
+        assert!(definition.validate(&json!(["ftp://atlaspharma.com"])).is_err());
+        assert!(definition.validate(&json!(["https://atlaspharma.com/app"])).is_err());
+        assert!(definition.validate(&json!("https://atlaspharma.com")).is_err());
+    }
+
+    #[test]
+    fn test_defaults_parse() {
+        for definition in SETTING_DEFINITIONS {
+            assert!(definition.parse_raw(definition.default).is_some(), "{}", definition.key);
+        }
+        let origins = SettingDefinition::find(CORS_ALLOWED_ORIGINS).unwrap();
+        assert_eq!(origins.parse_raw(" https://a.com , https://b.com,"), Some(json!(["https://a.com", "https://b.com"])));
+        let maintenance = SettingDefinition::find(MAINTENANCE_ENABLED).unwrap();
+        assert_eq!(maintenance.parse_raw(" ON "), Some(json!(true)));
+        assert_eq!(maintenance.parse_raw("0"), Some(json!(false)));
+        assert_eq!(maintenance.parse_raw("maybe"), None);
+    }
+}
@@ -0,0 +1,65 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// Materialized views kept fresh by the summary refresh job (see migration 092)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SummaryView {
+    PharmaManufacturers,
+    PharmaCategories,
+    OpenFdaManufacturers,
+    MarketplaceFacets,
+}
+
+impl SummaryView {
+    pub const ALL: [SummaryView; 4] = [
+        SummaryView::PharmaManufacturers,
+        SummaryView::PharmaCategories,
+        SummaryView::OpenFdaManufacturers,
+        SummaryView::MarketplaceFacets,
+    ];
+
+    /// Name of the materialized view, also its key in `summary_refreshes`
+    pub fn view_name(&self) -> &'static str {
+        match self {
+            SummaryView::PharmaManufacturers => "pharma_manufacturer_counts",
+            SummaryView::PharmaCategories => "pharma_category_counts",
+            SummaryView::OpenFdaManufacturers => "openfda_manufacturer_counts",
+            SummaryView::MarketplaceFacets => "marketplace_facet_counts",
+        }
+    }
+
+    pub fn parse(view_name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|view| view.view_name() == view_name)
+    }
+}
+
+/// A value read from a summary, with when the summary was last refreshed
+#[derive(Debug, Clone)]
+pub struct Summarized<T> {
+    pub value: T,
+    pub refreshed_at: Option<DateTime<Utc>>,
+}
+
+impl<T> Summarized<T> {
+    /// `refreshed_at` as an HTTP date, for the Last-Modified header of list responses
+    pub fn last_modified(&self) -> Option<String> {
+        self.refreshed_at.map(|at| at.format("%a, %d %b %Y %H:%M:%S GMT").to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_last_modified_is_an_http_date() {
+        let summary = Summarized { value: (), refreshed_at: Utc.with_ymd_and_hms(2026, 3, 5, 7, 8, 9).single() };
+        assert_eq!(summary.last_modified().as_deref(), Some("Thu, 05 Mar 2026 07:08:09 GMT"));
+
+        for view in SummaryView::ALL {
+            assert_eq!(SummaryView::parse(view.view_name()), Some(view));
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use serde::{Deserialize, Serialize};
+
+/// Outcome of a completed catalog sync, as checked by the anomaly guardrails
+#[derive(Debug, Clone, Default)]
+pub struct SyncRunStats {
+    /// Catalog entries before the run started
+    pub catalog_size: i64,
+    pub fetched: i64,
+    pub inserted: i64,
+    pub updated: i64,
+    pub failed: i64,
+    /// Entries deleted because the source no longer lists them
+    pub removed: i64,
+    /// Entries whose status changed (catalog change events of the run)
+    pub altered: i64,
+    /// Delta/incremental runs may legitimately write nothing
+    pub incremental: bool,
+}
+
+/// Limits beyond which a run is marked suspect
+#[derive(Debug, Clone)]
+pub struct SyncAnomalyThresholds {
+    /// Maximum share of the catalog a run may delete or alter (percent)
+    pub max_change_percent: f64,
+    /// Maximum share of fetched records that may fail conversion or upsert (percent)
+    pub max_error_percent: f64,
+    /// Change checks only apply once the catalog has at least this many entries
+    pub min_catalog_size: i64,
+}
+
+impl Default for SyncAnomalyThresholds {
+    fn default() -> Self {
+        Self {
+            max_change_percent: 20.0,
+            max_error_percent: 5.0,
+            min_catalog_size: 100,
+        }
+    }
+}
+
+impl SyncAnomalyThresholds {
+    /// Defaults, overridable through SYNC_ANOMALY_MAX_CHANGE_PERCENT,
+    /// SYNC_ANOMALY_MAX_ERROR_PERCENT and SYNC_ANOMALY_MIN_CATALOG_SIZE
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let env = |name: &str| std::env::var(name).ok().and_then(|v| v.parse::<f64>().ok());
+
+        Self {
+            max_change_percent: env("SYNC_ANOMALY_MAX_CHANGE_PERCENT").unwrap_or(defaults.max_change_percent),
+            max_error_percent: env("SYNC_ANOMALY_MAX_ERROR_PERCENT").unwrap_or(defaults.max_error_percent),
+            min_catalog_size: env("SYNC_ANOMALY_MIN_CATALOG_SIZE")
+                .map(|v| v as i64)
+                .unwrap_or(defaults.min_catalog_size),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SyncAnomaly {
+    /// The run deleted or altered too large a share of the catalog
+    MassChange { changed: i64, catalog_size: i64, percent: f64 },
+    /// The run inserted and updated nothing
+    ZeroInserts { fetched: i64 },
+    /// Too many fetched records failed
+    ErrorSpike { failed: i64, fetched: i64, percent: f64 },
+}
+
+impl SyncAnomaly {
+    /// Metric label
+    pub fn kind(&self) -> &'static str {
+        match self {
+            SyncAnomaly::MassChange { .. } => "mass_change",
+            SyncAnomaly::ZeroInserts { .. } => "zero_inserts",
+            SyncAnomaly::ErrorSpike { .. } => "error_spike",
+        }
+    }
+
+    pub fn description(&self) -> String {
+        match self {
+            SyncAnomaly::MassChange { changed, catalog_size, percent } => format!(
+                "{} of {} catalog entries ({:.1}%) were deleted or changed status",
+                changed, catalog_size, percent
+            ),
+            SyncAnomaly::ZeroInserts { fetched } => format!(
+                "No records were inserted or updated ({} fetched)",
+                fetched
+            ),
+            SyncAnomaly::ErrorSpike { failed, fetched, percent } => format!(
+                "{} of {} fetched records ({:.1}%) failed",
+                failed, fetched, percent
+            ),
+        }
+    }
+}
+
+/// Check a completed run against the thresholds; an empty result means the run looks normal
+pub fn assess_sync_run(stats: &SyncRunStats, thresholds: &SyncAnomalyThresholds) -> Vec<SyncAnomaly> {
+    let mut anomalies = Vec::new();
+
+    let changed = stats.removed + stats.altered;
+    if stats.catalog_size >= thresholds.min_catalog_size && stats.catalog_size > 0 {
+        let percent = changed as f64 * 100.0 / stats.catalog_size as f64;
+        if percent > thresholds.max_change_percent {
+            anomalies.push(SyncAnomaly::MassChange { changed, catalog_size: stats.catalog_size, percent });
+        }
+    }
+
+    if !stats.incremental && stats.inserted == 0 && stats.updated == 0 {
+        anomalies.push(SyncAnomaly::ZeroInserts { fetched: stats.fetched });
+    }
+
+    if stats.fetched > 0 {
+        let percent = stats.failed as f64 * 100.0 / stats.fetched as f64;
+        if percent > thresholds.max_error_percent {
+            anomalies.push(SyncAnomaly::ErrorSpike { failed: stats.failed, fetched: stats.fetched, percent });
+        }
+    }
+
+    anomalies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn healthy_run() -> SyncRunStats {
+        SyncRunStats {
+            catalog_size: 1000,
+            fetched: 1000,
+            inserted: 10,
+            updated: 990,
+            failed: 2,
+            removed: 5,
+            altered: 3,
+            incremental: false,
+        }
+    }
+
+    #[test]
+    fn test_healthy_run_has_no_anomalies() {
+        assert!(assess_sync_run(&healthy_run(), &SyncAnomalyThresholds::default()).is_empty());
+    }
+
+    #[test]
+    fn test_mass_removal() {
+        let stats = SyncRunStats { removed: 400, ..healthy_run() };
+        let anomalies = assess_sync_run(&stats, &SyncAnomalyThresholds::default());
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].kind(), "mass_change");
+
+        // Small catalogs (e.g. the first sync) are not checked for mass changes
+        let stats = SyncRunStats { catalog_size: 10, ..stats };
+        assert!(assess_sync_run(&stats, &SyncAnomalyThresholds::default()).is_empty());
+    }
+
+    #[test]
+    fn test_zero_inserts_and_error_spike() {
+        let stats = SyncRunStats { inserted: 0, updated: 0, failed: 1000, ..healthy_run() };
+        let kinds: Vec<&str> = assess_sync_run(&stats, &SyncAnomalyThresholds::default())
+            .iter()
+            .map(SyncAnomaly::kind)
+            .collect();
+        assert_eq!(kinds, vec!["zero_inserts", "error_spike"]);
+
+        // A delta run with nothing to do is normal
+        let stats = SyncRunStats { fetched: 0, inserted: 0, updated: 0, failed: 0, incremental: true, ..healthy_run() };
+        assert!(assess_sync_run(&stats, &SyncAnomalyThresholds::default()).is_empty());
+    }
+}
@@ -0,0 +1,213 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use utoipa::ToSchema;
+use sqlx::FromRow;
+
+/// In-progress syncs without a heartbeat for this long are reported as stalled
+pub const STALLED_AFTER_MINUTES: i64 = 10;
+
+/// Failed runs within the last 7 days at which a pipeline counts as failing
+pub const FAILING_RUNS_7D: i64 = 3;
+
+/// Latest run and 7-day history of one sync pipeline
+#[derive(Debug, Clone, FromRow)]
+pub struct SyncPipelineRow {
+    pub source: String,
+    pub last_status: Option<String>,
+    pub last_started_at: Option<DateTime<Utc>>,
+    pub last_completed_at: Option<DateTime<Utc>>,
+    pub last_heartbeat_at: Option<DateTime<Utc>>,
+    pub last_processing_time_ms: Option<i32>,
+    pub last_records_fetched: Option<i32>,
+    pub last_records_inserted: Option<i32>,
+    pub last_records_updated: Option<i32>,
+    pub last_records_failed: Option<i32>,
+    pub last_error: Option<String>,
+    pub last_success_at: Option<DateTime<Utc>>,
+    pub runs_7d: i64,
+    pub failed_7d: i64,
+    pub suspect_7d: i64,
+    pub scheduler_paused: bool,
+}
+
+/// Ordered from best to worst; the dashboard's overall health is the worst pipeline's
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncPipelineHealth {
+    /// Never synced (e.g. a source without credentials); doesn't affect overall health
+    NotSynced,
+    Healthy,
+    Degraded,
+    Stale,
+    Failing,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SyncPipelineStatus {
+    pub source: String,
+    pub health: SyncPipelineHealth,
+    pub issues: Vec<String>,
+    pub last_status: Option<String>,
+    pub last_started_at: Option<DateTime<Utc>>,
+    pub last_completed_at: Option<DateTime<Utc>>,
+    pub last_duration_ms: Option<i32>,
+    pub last_records_fetched: Option<i32>,
+    pub last_records_inserted: Option<i32>,
+    pub last_records_updated: Option<i32>,
+    pub last_records_failed: Option<i32>,
+    pub last_error: Option<String>,
+    pub last_success_at: Option<DateTime<Utc>>,
+    pub hours_since_success: Option<i64>,
+    pub runs_7d: i64,
+    pub failed_7d: i64,
+    pub suspect_7d: i64,
+    pub scheduler_paused: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SyncDashboard {
+    pub generated_at: DateTime<Utc>,
+    pub overall_health: SyncPipelineHealth,
+    pub stale_after_hours: i64,
+    pub pipelines: Vec<SyncPipelineStatus>,
+}
+
+impl SyncDashboard {
+    pub fn new(pipelines: Vec<SyncPipelineStatus>, stale_after_hours: i64) -> Self {
+        let overall_health = pipelines
+            .iter()
+            .map(|p| p.health)
+            .filter(|h| *h != SyncPipelineHealth::NotSynced)
+            .max()
+            .unwrap_or(SyncPipelineHealth::NotSynced);
+
+        Self { generated_at: Utc::now(), overall_health, stale_after_hours, pipelines }
+    }
+}
+
+/// Health of a pipeline from its latest run and recent history
+pub fn assess_pipeline(row: SyncPipelineRow, now: DateTime<Utc>, stale_after_hours: i64) -> SyncPipelineStatus {
+    let mut health = SyncPipelineHealth::Healthy;
+    let mut issues = Vec::new();
+    let mut flag = |level: SyncPipelineHealth, issue: String| {
+        health = health.max(level);
+        issues.push(issue);
+    };
+
+    let hours_since_success = row.last_success_at.map(|at| (now - at).num_hours());
+
+    match row.last_status.as_deref() {
+        None => flag(SyncPipelineHealth::NotSynced, "Never synced".to_string()),
+        Some("in_progress") => {
+            let last_seen = row.last_heartbeat_at.or(row.last_started_at).unwrap_or(now);
+            if now - last_seen > Duration::minutes(STALLED_AFTER_MINUTES) {
+                flag(
+                    SyncPipelineHealth::Failing,
+                    format!("Running sync has not reported progress for {} minutes", (now - last_seen).num_minutes()),
+                );
+            }
+        }
+        Some("failed") => flag(
+            SyncPipelineHealth::Degraded,
+            format!("Latest sync failed: {}", row.last_error.as_deref().unwrap_or("unknown error")),
+        ),
+        Some("suspect") => flag(
+            SyncPipelineHealth::Degraded,
+            "Latest sync was marked suspect by the anomaly guardrails".to_string(),
+        ),
+        Some(_) => {}
+    }
+
+    if row.failed_7d >= FAILING_RUNS_7D {
+        flag(SyncPipelineHealth::Failing, format!("{} failed syncs in the last 7 days", row.failed_7d));
+    }
+
+    match hours_since_success {
+        Some(hours) if hours > stale_after_hours => flag(
+            SyncPipelineHealth::Stale,
+            format!("Last successful sync was {} hours ago", hours),
+        ),
+        None if row.last_status.is_some() => flag(
+            SyncPipelineHealth::Stale,
+            "No successful sync on record".to_string(),
+        ),
+        _ => {}
+    }
+
+    if row.scheduler_paused {
+        issues.push("Scheduler is paused".to_string());
+    }
+
+    SyncPipelineStatus {
+        source: row.source,
+        health,
+        issues,
+        last_status: row.last_status,
+        last_started_at: row.last_started_at,
+        last_completed_at: row.last_completed_at,
+        last_duration_ms: row.last_processing_time_ms,
+        last_records_fetched: row.last_records_fetched,
+        last_records_inserted: row.last_records_inserted,
+        last_records_updated: row.last_records_updated,
+        last_records_failed: row.last_records_failed,
+        last_error: row.last_error,
+        last_success_at: row.last_success_at,
+        hours_since_success,
+        runs_7d: row.runs_7d,
+        failed_7d: row.failed_7d,
+        suspect_7d: row.suspect_7d,
+        scheduler_paused: row.scheduler_paused,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(status: Option<&str>, success_hours_ago: Option<i64>, failed_7d: i64) -> SyncPipelineRow {
+        let now = Utc::now();
+        SyncPipelineRow {
+            source: "openfda".to_string(),
+            last_status: status.map(str::to_string),
+            last_started_at: Some(now - Duration::minutes(30)),
+            last_completed_at: None,
+            last_heartbeat_at: Some(now - Duration::minutes(20)),
+            last_processing_time_ms: None,
+            last_records_fetched: None,
+            last_records_inserted: None,
+            last_records_updated: None,
+            last_records_failed: None,
+            last_error: None,
+            last_success_at: success_hours_ago.map(|h| now - Duration::hours(h)),
+            runs_7d: 0,
+            failed_7d,
+            suspect_7d: 0,
+            scheduler_paused: false,
+        }
+    }
+
+    #[test]
+    fn test_assess_pipeline() {
+        let now = Utc::now();
+        assert_eq!(assess_pipeline(row(Some("completed"), Some(2), 0), now, 192).health, SyncPipelineHealth::Healthy);
+        assert_eq!(assess_pipeline(row(Some("failed"), Some(2), 1), now, 192).health, SyncPipelineHealth::Degraded);
+        assert_eq!(assess_pipeline(row(Some("completed"), Some(300), 0), now, 192).health, SyncPipelineHealth::Stale);
+        assert_eq!(assess_pipeline(row(Some("failed"), Some(2), 3), now, 192).health, SyncPipelineHealth::Failing);
+        // Heartbeat 20 minutes old
+        assert_eq!(assess_pipeline(row(Some("in_progress"), Some(2), 0), now, 192).health, SyncPipelineHealth::Failing);
+        assert_eq!(assess_pipeline(row(None, None, 0), now, 192).health, SyncPipelineHealth::NotSynced);
+    }
+
+    #[test]
+    fn test_overall_health_ignores_unsynced_sources() {
+        let now = Utc::now();
+        let dashboard = SyncDashboard::new(
+            vec![
+                assess_pipeline(row(Some("completed"), Some(2), 0), now, 192),
+                assess_pipeline(row(None, None, 0), now, 192),
+            ],
+            192,
+        );
+        assert_eq!(dashboard.overall_health, SyncPipelineHealth::Healthy);
+    }
+}
@@ -0,0 +1,158 @@
+use std::collections::BTreeMap;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+
+/// Default and maximum number of upstream records a dry run fetches
+pub const DEFAULT_PREVIEW_LIMIT: usize = 500;
+pub const MAX_PREVIEW_LIMIT: usize = 5000;
+
+/// Number of record diffs included in a preview
+pub const PREVIEW_SAMPLE_SIZE: usize = 20;
+
+/// Result of a dry-run sync: what a real sync would write, without writing it
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncPreview {
+    pub source: String,
+    pub dry_run: bool,
+    pub records_fetched: i64,
+    pub would_insert: i64,
+    pub would_update: i64,
+    pub unchanged: i64,
+    /// Upstream records that could not be converted (a sign of format changes)
+    pub invalid: i64,
+    /// How many updated records change each field
+    pub field_change_counts: BTreeMap<String, i64>,
+    pub sample_diffs: Vec<RecordDiff>,
+    pub generated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordDiff {
+    pub identifier: String,
+    pub product_name: String,
+    /// "insert" or "update"
+    pub change: String,
+    pub fields: Vec<FieldChange>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub old: Value,
+    pub new: Value,
+}
+
+impl SyncPreview {
+    pub fn new(source: &str) -> Self {
+        Self {
+            source: source.to_string(),
+            dry_run: true,
+            records_fetched: 0,
+            would_insert: 0,
+            would_update: 0,
+            unchanged: 0,
+            invalid: 0,
+            field_change_counts: BTreeMap::new(),
+            sample_diffs: Vec::new(),
+            generated_at: Utc::now(),
+        }
+    }
+
+    /// Count a fetched record against its stored counterpart (`None` when new).
+    /// Both sides are the serialized catalog entry; only `fields` are compared.
+    pub fn record(
+        &mut self,
+        identifier: &str,
+        product_name: &str,
+        existing: Option<&Value>,
+        incoming: &Value,
+        fields: &[&str],
+    ) {
+        let (change, changes) = match existing {
+            None => {
+                self.would_insert += 1;
+                let changes = fields
+                    .iter()
+                    .filter_map(|field| {
+                        let new = incoming.get(*field).cloned().unwrap_or(Value::Null);
+                        (!new.is_null()).then(|| FieldChange { field: field.to_string(), old: Value::Null, new })
+                    })
+                    .collect();
+                ("insert", changes)
+            }
+            Some(existing) => {
+                let changes = diff_fields(existing, incoming, fields);
+                if changes.is_empty() {
+                    self.unchanged += 1;
+                    return;
+                }
+                self.would_update += 1;
+                for change in &changes {
+                    *self.field_change_counts.entry(change.field.clone()).or_insert(0) += 1;
+                }
+                ("update", changes)
+            }
+        };
+
+        if self.sample_diffs.len() < PREVIEW_SAMPLE_SIZE {
+            self.sample_diffs.push(RecordDiff {
+                identifier: identifier.to_string(),
+                product_name: product_name.to_string(),
+                change: change.to_string(),
+                fields: changes,
+            });
+        }
+    }
+}
+
+/// Fields whose values differ between two serialized records; missing fields count as null
+pub fn diff_fields(old: &Value, new: &Value, fields: &[&str]) -> Vec<FieldChange> {
+    fields
+        .iter()
+        .filter_map(|field| {
+            let old_value = old.get(*field).cloned().unwrap_or(Value::Null);
+            let new_value = new.get(*field).cloned().unwrap_or(Value::Null);
+            (old_value != new_value).then(|| FieldChange {
+                field: field.to_string(),
+                old: old_value,
+                new: new_value,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const FIELDS: &[&str] = &["brand_name", "strength", "route"];
+
+    #[test]
+    fn test_diff_fields() {
+        let old = json!({ "brand_name": "Advil", "strength": "200 mg", "id": "a" });
+        let new = json!({ "brand_name": "Advil", "strength": "400 mg", "route": ["ORAL"], "id": "b" });
+
+        let changes = diff_fields(&old, &new, FIELDS);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].field, "strength");
+        assert_eq!(changes[1], FieldChange { field: "route".to_string(), old: Value::Null, new: json!(["ORAL"]) });
+    }
+
+    #[test]
+    fn test_preview_counts() {
+        let stored = json!({ "brand_name": "Advil", "strength": "200 mg" });
+        let mut preview = SyncPreview::new("openfda");
+
+        preview.record("0001", "Advil", Some(&stored), &stored, FIELDS);
+        preview.record("0002", "Advil", Some(&stored), &json!({ "brand_name": "Advil", "strength": "400 mg" }), FIELDS);
+        preview.record("0003", "Motrin", None, &json!({ "brand_name": "Motrin" }), FIELDS);
+
+        assert_eq!((preview.would_insert, preview.would_update, preview.unchanged), (1, 1, 1));
+        assert_eq!(preview.field_change_counts.get("strength"), Some(&1));
+        assert_eq!(preview.sample_diffs.len(), 2);
+        assert_eq!(preview.sample_diffs[1].change, "insert");
+        assert_eq!(preview.sample_diffs[1].fields.len(), 1);
+    }
+}
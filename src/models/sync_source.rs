@@ -0,0 +1,82 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Sources whose sync settings can be edited at runtime
+pub const SYNC_SOURCES: [&str; 5] = ["openfda", "ema", "mhra", "health_canada", "swissmedic"];
+
+/// Stored settings of a sync source; NULL columns fall back to the environment
+#[derive(Debug, Clone, FromRow)]
+pub struct SyncSourceSettings {
+    pub source: String,
+    pub requests_per_minute: Option<i32>,
+    pub burst_limit: Option<i32>,
+    pub api_key_encrypted: Option<String>,
+    pub scheduler_paused: bool,
+    pub paused_reason: Option<String>,
+    pub paused_at: Option<DateTime<Utc>>,
+    pub paused_by: Option<Uuid>,
+    pub updated_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Settings applied to a sync run (API key decrypted)
+#[derive(Debug, Clone, Default)]
+pub struct SourceRuntimeSettings {
+    pub requests_per_minute: Option<u32>,
+    pub burst_limit: Option<u32>,
+    pub api_key: Option<String>,
+    pub scheduler_paused: bool,
+}
+
+/// Admin view of a source's settings; the API key itself is never returned
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SyncSourceSettingsResponse {
+    pub source: String,
+    pub requests_per_minute: Option<i32>,
+    pub burst_limit: Option<i32>,
+    pub has_api_key: bool,
+    pub scheduler_paused: bool,
+    pub paused_reason: Option<String>,
+    pub paused_at: Option<DateTime<Utc>>,
+    pub paused_by: Option<Uuid>,
+    pub updated_by: Option<Uuid>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<SyncSourceSettings> for SyncSourceSettingsResponse {
+    fn from(settings: SyncSourceSettings) -> Self {
+        Self {
+            source: settings.source,
+            requests_per_minute: settings.requests_per_minute,
+            burst_limit: settings.burst_limit,
+            has_api_key: settings.api_key_encrypted.is_some(),
+            scheduler_paused: settings.scheduler_paused,
+            paused_reason: settings.paused_reason,
+            paused_at: settings.paused_at,
+            paused_by: settings.paused_by,
+            updated_by: settings.updated_by,
+            updated_at: settings.updated_at,
+        }
+    }
+}
+
+/// PUT body: omitted fields are left unchanged; `clear_*` resets to the environment default
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateSyncSourceRequest {
+    pub requests_per_minute: Option<i32>,
+    pub burst_limit: Option<i32>,
+    pub api_key: Option<String>,
+    #[serde(default)]
+    pub clear_rate_limit: bool,
+    #[serde(default)]
+    pub clear_api_key: bool,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PauseSchedulerRequest {
+    pub reason: Option<String>,
+}
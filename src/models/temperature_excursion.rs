@@ -0,0 +1,761 @@
+/// GDP temperature monitoring models: data logger readings attached to shipments
+/// (marketplace transactions), excursions against the product's storage conditions,
+/// excursion disputes and the self-inspection summary
+
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Readings accepted from one logger file
+pub const MAX_LOGGER_READINGS: usize = 50_000;
+
+/// Activation energy / gas constant (ΔH/R, in K) of the mean kinetic temperature,
+/// for the 83.144 kJ/mol that USP <1160> and ICH Q1A assume
+const MKT_ACTIVATION_RATIO_K: f64 = 10_000.0;
+
+const KELVIN_OFFSET: f64 = 273.15;
+
+// ============================================================================
+// STORAGE CONDITIONS
+// ============================================================================
+
+/// Allowed temperature range in °C; an open bound is not checked
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct StorageRange {
+    pub min_c: Option<f64>,
+    pub max_c: Option<f64>,
+}
+
+impl StorageRange {
+    pub fn classify(&self, temperature_c: f64) -> Option<ExcursionKind> {
+        if self.max_c.is_some_and(|max| temperature_c > max) {
+            Some(ExcursionKind::Above)
+        } else if self.min_c.is_some_and(|min| temperature_c < min) {
+            Some(ExcursionKind::Below)
+        } else {
+            None
+        }
+    }
+}
+
+const NUMBER: &str = r"(-?\d+(?:[.,]\d+)?)";
+const CELSIUS: &str = r"\s*(?:(?:°|º|˚)\s*C\b|C\b|degrees?\s*(?:C\b|celsius))";
+
+/// "2-8 °C", "2 °C to 8 °C", "between 15 and 25°C", "-25°C – -15°C"
+static RANGE_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(&format!(
+        r"(?i){NUMBER}(?:\s*(?:°|º|˚)\s*C?)?\s*(?:-|–|—|to|and)\s*{NUMBER}{CELSIUS}"
+    ))
+    .expect("valid storage range regex")
+});
+
+/// "Store below 25 °C", "Do not store above 30°C", "at or below -20 °C"
+static MAX_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(&format!(
+        r"(?i)(?:below|under|not\s+(?:to\s+)?exceed(?:ing)?|not\s+(?:store\s+)?above|up\s+to|no\s+(?:higher|warmer)\s+than|maximum(?:\s+of)?|max\.?|≤|<=|<)\s*\+?{NUMBER}{CELSIUS}"
+    ))
+    .expect("valid maximum temperature regex")
+});
+
+/// "above 2 °C", "not below 15°C", "at least 2 °C"
+static MIN_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(&format!(
+        r"(?i)(?:above|not\s+below|at\s+least|minimum(?:\s+of)?|min\.?|no\s+(?:lower|colder)\s+than|≥|>=|>)\s*\+?{NUMBER}{CELSIUS}"
+    ))
+    .expect("valid minimum temperature regex")
+});
+
+/// Range of a product's free-text storage requirements. An explicit range wins over
+/// bounds ("below 25 °C"), which win over the usual terms (refrigerate, freeze, room
+/// temperature). None when the text states no temperature.
+pub fn parse_storage_range(storage_requirements: &str) -> Option<StorageRange> {
+    let text = storage_requirements;
+    let number = |s: &str| s.replace(',', ".").parse::<f64>().ok();
+
+    if let Some(caps) = RANGE_PATTERN.captures(text) {
+        if let (Some(a), Some(b)) = (number(&caps[1]), number(&caps[2])) {
+            return Some(StorageRange { min_c: Some(a.min(b)), max_c: Some(a.max(b)) });
+        }
+    }
+
+    let max_c = MAX_PATTERN.captures(text).and_then(|caps| number(&caps[1]));
+    // "not store above 30 °C" must not read as a minimum of 30 °C
+    let without_max = MAX_PATTERN.replace_all(text, " ");
+    let mut min_c = MIN_PATTERN.captures(&without_max).and_then(|caps| number(&caps[1]));
+
+    let lower = text.to_lowercase();
+    if min_c.is_none() && (lower.contains("do not freeze") || lower.contains("protect from freezing")) {
+        min_c = Some(0.0);
+    }
+    if max_c.is_some() || min_c.is_some() {
+        return Some(StorageRange { min_c, max_c });
+    }
+
+    if lower.contains("refrigerat") || lower.contains("fridge") {
+        Some(StorageRange { min_c: Some(2.0), max_c: Some(8.0) })
+    } else if lower.contains("freezer") || lower.contains("frozen") {
+        Some(StorageRange { min_c: None, max_c: Some(-15.0) })
+    } else if lower.contains("room temperature") {
+        Some(StorageRange { min_c: Some(15.0), max_c: Some(25.0) })
+    } else {
+        None
+    }
+}
+
+// ============================================================================
+// LOGGER DATA
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum LoggerDataFormat {
+    Csv,
+    Json,
+}
+
+impl LoggerDataFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LoggerDataFormat::Csv => "csv",
+            LoggerDataFormat::Json => "json",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct TemperatureReading {
+    pub recorded_at: DateTime<Utc>,
+    pub temperature_c: f64,
+}
+
+#[derive(Debug)]
+pub struct ParsedLoggerData {
+    /// Sorted by time, one reading per timestamp
+    pub readings: Vec<TemperatureReading>,
+    pub skipped_rows: usize,
+}
+
+/// Readings of a logger export. Timestamps without an offset are logger local time at
+/// `utc_offset_minutes` (UTC when absent); Fahrenheit columns are converted to °C.
+pub fn parse_logger_data(
+    format: LoggerDataFormat,
+    data: &str,
+    utc_offset_minutes: Option<i32>,
+) -> Result<ParsedLoggerData, String> {
+    let offset = FixedOffset::east_opt(utc_offset_minutes.unwrap_or(0) * 60)
+        .ok_or_else(|| "Invalid UTC offset".to_string())?;
+
+    let (mut readings, skipped_rows) = match format {
+        LoggerDataFormat::Csv => parse_logger_csv(data, &offset)?,
+        LoggerDataFormat::Json => parse_logger_json(data, &offset)?,
+    };
+
+    if readings.len() > MAX_LOGGER_READINGS {
+        return Err(format!("Logger data has more than {} readings", MAX_LOGGER_READINGS));
+    }
+    readings.sort_by_key(|r| r.recorded_at);
+    readings.dedup_by_key(|r| r.recorded_at);
+    if readings.len() < 2 {
+        return Err("Logger data needs at least two readings".to_string());
+    }
+
+    Ok(ParsedLoggerData { readings, skipped_rows })
+}
+
+fn parse_logger_csv(data: &str, offset: &FixedOffset) -> Result<(Vec<TemperatureReading>, usize), String> {
+    let data = data.strip_prefix('\u{feff}').unwrap_or(data);
+    let header_line = data.lines().next().unwrap_or_default();
+    let delimiter = [b';', b'\t']
+        .into_iter()
+        .find(|d| header_line.contains(*d as char) && !header_line.contains(','))
+        .unwrap_or(b',');
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(data.as_bytes());
+
+    let headers: Vec<String> = reader
+        .headers()
+        .map_err(|e| format!("Could not read header row: {}", e))?
+        .iter()
+        .map(|h| h.to_lowercase())
+        .collect();
+
+    let temperature_index = headers
+        .iter()
+        .position(|h| h.contains("temp") || h.contains("°c") || h.contains("°f") || h == "value")
+        .ok_or_else(|| "No temperature column in the header row".to_string())?;
+    let fahrenheit = is_fahrenheit(&headers[temperature_index]);
+
+    let position = |names: &[&str]| headers.iter().position(|h| names.contains(&h.as_str()));
+    let timestamp_columns = match position(&["timestamp", "datetime", "date_time", "date time", "recorded_at", "time stamp"]) {
+        Some(index) => vec![index],
+        None => match (position(&["date"]), position(&["time"])) {
+            (Some(date), Some(time)) => vec![date, time],
+            _ => vec![headers
+                .iter()
+                .enumerate()
+                .position(|(i, h)| i != temperature_index && (h.contains("date") || h.contains("time")))
+                .ok_or_else(|| "No timestamp column in the header row".to_string())?],
+        },
+    };
+
+    let mut readings = Vec::new();
+    let mut skipped = 0;
+    for record in reader.records() {
+        let Ok(record) = record else {
+            skipped += 1;
+            continue;
+        };
+        if record.iter().all(str::is_empty) {
+            continue;
+        }
+
+        let timestamp = timestamp_columns
+            .iter()
+            .map(|&i| record.get(i).unwrap_or_default())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let reading = parse_logger_timestamp(&timestamp, offset).zip(
+            record
+                .get(temperature_index)
+                .and_then(|v| parse_temperature(v, fahrenheit)),
+        );
+        match reading {
+            Some((recorded_at, temperature_c)) => readings.push(TemperatureReading { recorded_at, temperature_c }),
+            None => skipped += 1,
+        }
+    }
+
+    Ok((readings, skipped))
+}
+
+/// `[{...}]` or `{"readings": [{...}]}`; each reading has a timestamp (`timestamp`,
+/// `recorded_at`, `time` or `datetime`; text or Unix seconds) and a temperature
+/// (`temperature_c`, `temperature`, `temp` or `value`, or `temperature_f`)
+fn parse_logger_json(data: &str, offset: &FixedOffset) -> Result<(Vec<TemperatureReading>, usize), String> {
+    let value: serde_json::Value = serde_json::from_str(data).map_err(|e| format!("Invalid JSON: {}", e))?;
+    let items = match &value {
+        serde_json::Value::Array(items) => items,
+        serde_json::Value::Object(map) => map
+            .get("readings")
+            .and_then(|r| r.as_array())
+            .ok_or_else(|| "Expected an array of readings or a \"readings\" array".to_string())?,
+        _ => return Err("Expected an array of readings or a \"readings\" array".to_string()),
+    };
+
+    let mut readings = Vec::new();
+    let mut skipped = 0;
+    for item in items {
+        let field = |names: &[&str]| names.iter().find_map(|name| item.get(*name)).filter(|v| !v.is_null());
+
+        let recorded_at = field(&["timestamp", "recorded_at", "time", "datetime"]).and_then(|v| match v {
+            serde_json::Value::String(s) => parse_logger_timestamp(s, offset),
+            serde_json::Value::Number(n) => n.as_i64().and_then(|secs| DateTime::from_timestamp(secs, 0)),
+            _ => None,
+        });
+        let fahrenheit = field(&["unit"]).and_then(|u| u.as_str()).is_some_and(is_fahrenheit);
+        let temperature_c = match field(&["temperature_f"]) {
+            Some(v) => json_number(v).map(fahrenheit_to_celsius),
+            None => field(&["temperature_c", "temperature", "temp", "value"])
+                .and_then(json_number)
+                .map(|t| if fahrenheit { fahrenheit_to_celsius(t) } else { t }),
+        };
+
+        match recorded_at.zip(temperature_c) {
+            Some((recorded_at, temperature_c)) => readings.push(TemperatureReading { recorded_at, temperature_c }),
+            None => skipped += 1,
+        }
+    }
+
+    Ok((readings, skipped))
+}
+
+fn json_number(value: &serde_json::Value) -> Option<f64> {
+    match value {
+        serde_json::Value::Number(n) => n.as_f64(),
+        serde_json::Value::String(s) => parse_temperature(s, false),
+        _ => None,
+    }
+}
+
+fn is_fahrenheit(label: &str) -> bool {
+    let label = label.trim().to_lowercase();
+    label == "f"
+        || label.contains("fahrenheit")
+        || label.contains("°f")
+        || label.ends_with("(f)")
+        || label.ends_with("_f")
+        || label.ends_with(" f")
+}
+
+fn fahrenheit_to_celsius(fahrenheit: f64) -> f64 {
+    (fahrenheit - 32.0) * 5.0 / 9.0
+}
+
+fn parse_temperature(value: &str, fahrenheit: bool) -> Option<f64> {
+    let value = value
+        .trim()
+        .trim_end_matches(|c: char| c.is_alphabetic() || c == '°' || c.is_whitespace())
+        .replace(',', ".");
+    let temperature = value.parse::<f64>().ok().filter(|t| t.is_finite())?;
+    Some(if fahrenheit { fahrenheit_to_celsius(temperature) } else { temperature })
+}
+
+/// RFC 3339, ISO-like, European day-first or Unix seconds
+fn parse_logger_timestamp(value: &str, offset: &FixedOffset) -> Option<DateTime<Utc>> {
+    const FORMATS: [&str; 12] = [
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%d %H:%M:%S%.f",
+        "%Y-%m-%dT%H:%M:%S%.f",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M",
+        "%Y/%m/%d %H:%M:%S",
+        "%Y/%m/%d %H:%M",
+        "%d/%m/%Y %H:%M:%S",
+        "%d/%m/%Y %H:%M",
+        "%d.%m.%Y %H:%M:%S",
+        "%d.%m.%Y %H:%M",
+    ];
+
+    let value = value.trim();
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Some(timestamp.with_timezone(&Utc));
+    }
+    if !value.is_empty() && value.chars().all(|c| c.is_ascii_digit()) {
+        return value.parse::<i64>().ok().and_then(|secs| DateTime::from_timestamp(secs, 0));
+    }
+
+    FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .and_then(|naive| offset.from_local_datetime(&naive).single())
+        .map(|timestamp| timestamp.with_timezone(&Utc))
+}
+
+// ============================================================================
+// EXCURSION ANALYSIS
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExcursionKind {
+    Above,
+    Below,
+}
+
+impl ExcursionKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExcursionKind::Above => "above",
+            ExcursionKind::Below => "below",
+        }
+    }
+}
+
+/// A run of consecutive readings outside the range. It lasts from the first reading
+/// outside until the first one back inside (or the last reading, when still ongoing).
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct DetectedExcursion {
+    pub kind: ExcursionKind,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+    pub duration_seconds: i64,
+    pub reading_count: i32,
+    /// Highest (above) or lowest (below) temperature
+    pub extreme_temp_c: f64,
+    /// How far the extreme is outside the range
+    pub deviation_c: f64,
+    pub ongoing: bool,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TemperatureAnalysis {
+    pub reading_count: i32,
+    pub first_reading_at: DateTime<Utc>,
+    pub last_reading_at: DateTime<Utc>,
+    pub min_temp_c: f64,
+    pub max_temp_c: f64,
+    pub mean_temp_c: f64,
+    pub mean_kinetic_temp_c: f64,
+    pub longest_gap_seconds: i64,
+    pub total_excursion_seconds: i64,
+    pub excursions: Vec<DetectedExcursion>,
+}
+
+/// Excursions, summary statistics and mean kinetic temperature of time-ordered readings
+/// (at least one)
+pub fn analyze_readings(readings: &[TemperatureReading], range: &StorageRange) -> TemperatureAnalysis {
+    let mut excursions: Vec<DetectedExcursion> = Vec::new();
+    let mut current: Option<DetectedExcursion> = None;
+
+    for reading in readings {
+        let kind = range.classify(reading.temperature_c);
+        if let Some(open) = current.as_mut().filter(|open| Some(open.kind) == kind) {
+            open.reading_count += 1;
+            open.extreme_temp_c = match open.kind {
+                ExcursionKind::Above => open.extreme_temp_c.max(reading.temperature_c),
+                ExcursionKind::Below => open.extreme_temp_c.min(reading.temperature_c),
+            };
+            continue;
+        }
+
+        if let Some(closed) = current.take() {
+            excursions.push(close_excursion(closed, reading.recorded_at, range, false));
+        }
+        current = kind.map(|kind| DetectedExcursion {
+            kind,
+            started_at: reading.recorded_at,
+            ended_at: reading.recorded_at,
+            duration_seconds: 0,
+            reading_count: 1,
+            extreme_temp_c: reading.temperature_c,
+            deviation_c: 0.0,
+            ongoing: false,
+        });
+    }
+    let last = readings[readings.len() - 1];
+    if let Some(open) = current.take() {
+        excursions.push(close_excursion(open, last.recorded_at, range, true));
+    }
+
+    let temperatures = readings.iter().map(|r| r.temperature_c);
+    let count = readings.len() as f64;
+    let mean_exp = readings
+        .iter()
+        .map(|r| (-MKT_ACTIVATION_RATIO_K / (r.temperature_c + KELVIN_OFFSET)).exp())
+        .sum::<f64>()
+        / count;
+
+    TemperatureAnalysis {
+        reading_count: readings.len() as i32,
+        first_reading_at: readings[0].recorded_at,
+        last_reading_at: last.recorded_at,
+        min_temp_c: round2(temperatures.clone().fold(f64::INFINITY, f64::min)),
+        max_temp_c: round2(temperatures.clone().fold(f64::NEG_INFINITY, f64::max)),
+        mean_temp_c: round2(temperatures.sum::<f64>() / count),
+        mean_kinetic_temp_c: round2(MKT_ACTIVATION_RATIO_K / -mean_exp.ln() - KELVIN_OFFSET),
+        longest_gap_seconds: readings
+            .windows(2)
+            .map(|pair| (pair[1].recorded_at - pair[0].recorded_at).num_seconds())
+            .max()
+            .unwrap_or(0),
+        total_excursion_seconds: excursions.iter().map(|e| e.duration_seconds).sum(),
+        excursions,
+    }
+}
+
+fn close_excursion(
+    mut excursion: DetectedExcursion,
+    ended_at: DateTime<Utc>,
+    range: &StorageRange,
+    ongoing: bool,
+) -> DetectedExcursion {
+    let limit = match excursion.kind {
+        ExcursionKind::Above => range.max_c.unwrap_or(excursion.extreme_temp_c),
+        ExcursionKind::Below => range.min_c.unwrap_or(excursion.extreme_temp_c),
+    };
+    excursion.ended_at = ended_at;
+    excursion.duration_seconds = (ended_at - excursion.started_at).num_seconds();
+    excursion.deviation_c = round2((excursion.extreme_temp_c - limit).abs());
+    excursion.extreme_temp_c = round2(excursion.extreme_temp_c);
+    excursion.ongoing = ongoing;
+    excursion
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+// ============================================================================
+// DATABASE MODELS
+// ============================================================================
+
+/// Logger data attached to a shipment, with its analysis (readings not included)
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct TemperatureLog {
+    pub id: Uuid,
+    pub transaction_id: Uuid,
+    pub uploaded_by: Option<Uuid>,
+    pub logger_serial: Option<String>,
+    pub source_format: String,
+    /// 'product' (parsed storage requirements) or 'manual'
+    pub range_source: String,
+    pub min_allowed_c: Option<f64>,
+    pub max_allowed_c: Option<f64>,
+    pub reading_count: i32,
+    pub skipped_rows: i32,
+    pub first_reading_at: DateTime<Utc>,
+    pub last_reading_at: DateTime<Utc>,
+    pub min_temp_c: f64,
+    pub max_temp_c: f64,
+    pub mean_temp_c: f64,
+    pub mean_kinetic_temp_c: f64,
+    pub longest_gap_seconds: i64,
+    pub excursion_count: i32,
+    pub total_excursion_seconds: i64,
+    pub notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct TemperatureExcursion {
+    pub id: Uuid,
+    pub temperature_log_id: Uuid,
+    pub transaction_id: Uuid,
+    pub kind: String,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+    pub duration_seconds: i64,
+    pub reading_count: i32,
+    pub extreme_temp_c: f64,
+    pub deviation_c: f64,
+    pub ongoing: bool,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct TransactionDispute {
+    pub id: Uuid,
+    pub transaction_id: Uuid,
+    pub raised_by: Option<Uuid>,
+    pub reason: String,
+    pub temperature_log_id: Option<Uuid>,
+    pub description: String,
+    /// Excursion report at the time the dispute was opened
+    pub evidence: serde_json::Value,
+    /// open, accepted, rejected, withdrawn
+    pub status: String,
+    pub resolution: Option<String>,
+    pub resolved_by: Option<Uuid>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+// ============================================================================
+// REQUEST / RESPONSE MODELS
+// ============================================================================
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct UploadTemperatureLogRequest {
+    pub format: LoggerDataFormat,
+    /// Logger export (CSV with a header row, or JSON)
+    #[validate(length(min = 1, max = 2000000, message = "Logger data must be 1-2000000 characters"))]
+    pub data: String,
+    #[validate(length(max = 100, message = "Logger serial must be at most 100 characters"))]
+    pub logger_serial: Option<String>,
+    /// Offset of timestamps without one; UTC when absent
+    #[validate(range(min = -840, max = 840, message = "UTC offset must be between -840 and 840 minutes"))]
+    pub utc_offset_minutes: Option<i32>,
+    /// Allowed range when the product's storage requirements state none (or to override them)
+    #[validate(range(min = -100.0, max = 100.0, message = "Temperature must be between -100 and 100 °C"))]
+    pub min_temp_c: Option<f64>,
+    #[validate(range(min = -100.0, max = 100.0, message = "Temperature must be between -100 and 100 °C"))]
+    pub max_temp_c: Option<f64>,
+    #[validate(length(max = 2000, message = "Notes must be at most 2000 characters"))]
+    pub notes: Option<String>,
+}
+
+impl UploadTemperatureLogRequest {
+    pub fn manual_range(&self) -> Option<StorageRange> {
+        (self.min_temp_c.is_some() || self.max_temp_c.is_some())
+            .then_some(StorageRange { min_c: self.min_temp_c, max_c: self.max_temp_c })
+    }
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct OpenDisputeRequest {
+    #[validate(length(min = 1, max = 5000, message = "Description must be 1-5000 characters"))]
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DisputeOutcome {
+    /// Seller accepts the claim
+    Accepted,
+    /// Seller rejects the claim
+    Rejected,
+    /// Buyer withdraws the claim
+    Withdrawn,
+}
+
+impl DisputeOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DisputeOutcome::Accepted => "accepted",
+            DisputeOutcome::Rejected => "rejected",
+            DisputeOutcome::Withdrawn => "withdrawn",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct ResolveDisputeRequest {
+    pub outcome: DisputeOutcome,
+    #[validate(length(min = 1, max = 5000, message = "Resolution must be 1-5000 characters"))]
+    pub resolution: String,
+}
+
+/// Product and lot a shipment carried
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct ShipmentProduct {
+    pub transaction_id: Uuid,
+    pub seller_id: Uuid,
+    pub buyer_id: Uuid,
+    pub pharmaceutical_id: Uuid,
+    pub brand_name: String,
+    pub generic_name: String,
+    pub ndc_code: Option<String>,
+    pub batch_number: String,
+    pub quantity: i32,
+    pub storage_requirements: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ExcursionReport {
+    pub shipment: ShipmentProduct,
+    pub log: TemperatureLog,
+    pub excursions: Vec<TemperatureExcursion>,
+    pub within_limits: bool,
+    pub disputes: Vec<TransactionDispute>,
+    pub generated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TemperatureSummaryQuery {
+    /// Inclusive; defaults to a year before `to`
+    pub from: Option<NaiveDate>,
+    /// Inclusive; defaults to today
+    pub to: Option<NaiveDate>,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct ProductExcursionSummary {
+    pub pharmaceutical_id: Uuid,
+    pub brand_name: String,
+    pub generic_name: String,
+    pub shipments_monitored: i64,
+    pub shipments_with_excursions: i64,
+    pub excursion_count: i64,
+    pub total_excursion_seconds: i64,
+}
+
+/// GDP self-inspection figures of the user's shipments (as seller or buyer)
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TemperatureSummary {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub shipments_monitored: i64,
+    pub logs_uploaded: i64,
+    pub shipments_with_excursions: i64,
+    pub excursion_count: i64,
+    pub excursions_above: i64,
+    pub excursions_below: i64,
+    pub total_excursion_seconds: i64,
+    pub longest_excursion_seconds: i64,
+    pub disputes_opened: i64,
+    pub disputes_open: i64,
+    pub disputes_accepted: i64,
+    pub disputes_rejected: i64,
+    pub disputes_withdrawn: i64,
+    pub by_product: Vec<ProductExcursionSummary>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(min_c: Option<f64>, max_c: Option<f64>) -> Option<StorageRange> {
+        Some(StorageRange { min_c, max_c })
+    }
+
+    #[test]
+    fn test_parse_storage_range() {
+        assert_eq!(parse_storage_range("Store refrigerated at 2-8°C"), range(Some(2.0), Some(8.0)));
+        assert_eq!(
+            parse_storage_range("Store at 20° to 25°C (68° to 77°F)"),
+            range(Some(20.0), Some(25.0))
+        );
+        assert_eq!(parse_storage_range("Store between 15 and 25 °C"), range(Some(15.0), Some(25.0)));
+        assert_eq!(parse_storage_range("Keep at -25 °C – -15 °C"), range(Some(-25.0), Some(-15.0)));
+        assert_eq!(parse_storage_range("Do not store above 30°C"), range(None, Some(30.0)));
+        assert_eq!(
+            parse_storage_range("Store below 25 °C. Do not freeze."),
+            range(Some(0.0), Some(25.0))
+        );
+        assert_eq!(parse_storage_range("Store in a refrigerator"), range(Some(2.0), Some(8.0)));
+        assert_eq!(parse_storage_range("Protect from light"), None);
+    }
+
+    #[test]
+    fn test_parse_logger_csv() {
+        let csv = "Date;Time;Temperature (°F)\n\
+                   16.10.2026;08:00;41\n\
+                   16.10.2026;08:05;46,4\n\
+                   16.10.2026;08:10;n/a\n";
+        let parsed = parse_logger_data(LoggerDataFormat::Csv, csv, Some(120)).unwrap();
+
+        assert_eq!(parsed.skipped_rows, 1);
+        assert_eq!(parsed.readings.len(), 2);
+        assert_eq!(parsed.readings[0].recorded_at.to_rfc3339(), "2026-10-16T06:00:00+00:00");
+        assert!((parsed.readings[0].temperature_c - 5.0).abs() < 1e-9);
+        assert!((parsed.readings[1].temperature_c - 8.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_parse_logger_json() {
+        let json = r#"{"readings": [
+            {"timestamp": "2026-10-16T08:05:00Z", "temperature_c": 6.5},
+            {"timestamp": 1792137600, "temp": "4.0"},
+            {"timestamp": "2026-10-16T08:00:00Z"}
+        ]}"#;
+        let parsed = parse_logger_data(LoggerDataFormat::Json, json, None).unwrap();
+
+        assert_eq!(parsed.skipped_rows, 1);
+        assert_eq!(parsed.readings.len(), 2);
+        assert!(parsed.readings[0].recorded_at < parsed.readings[1].recorded_at);
+
+        assert!(parse_logger_data(LoggerDataFormat::Json, "[]", None).is_err());
+    }
+
+    #[test]
+    fn test_analyze_readings() {
+        let start = DateTime::parse_from_rfc3339("2026-10-16T08:00:00Z").unwrap().with_timezone(&Utc);
+        let readings: Vec<TemperatureReading> = [5.0, 9.0, 11.5, 7.0, 1.0, 1.5]
+            .iter()
+            .enumerate()
+            .map(|(i, &temperature_c)| TemperatureReading {
+                recorded_at: start + chrono::Duration::minutes(5 * i as i64),
+                temperature_c,
+            })
+            .collect();
+        let analysis = analyze_readings(&readings, &StorageRange { min_c: Some(2.0), max_c: Some(8.0) });
+
+        assert_eq!(analysis.excursions.len(), 2);
+        let above = &analysis.excursions[0];
+        assert_eq!(above.kind, ExcursionKind::Above);
+        assert_eq!((above.reading_count, above.duration_seconds), (2, 600));
+        assert_eq!((above.extreme_temp_c, above.deviation_c), (11.5, 3.5));
+        assert!(!above.ongoing);
+
+        let below = &analysis.excursions[1];
+        assert_eq!(below.kind, ExcursionKind::Below);
+        assert_eq!((below.duration_seconds, below.deviation_c), (300, 1.0));
+        assert!(below.ongoing);
+
+        assert_eq!(analysis.total_excursion_seconds, 900);
+        assert_eq!(analysis.longest_gap_seconds, 300);
+        assert_eq!((analysis.min_temp_c, analysis.max_temp_c), (1.0, 11.5));
+        assert!(analysis.mean_kinetic_temp_c > analysis.mean_temp_c);
+    }
+}
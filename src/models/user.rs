@@ -1,11 +1,12 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use sqlx::FromRow;
 use uuid::Uuid;
 use validator::Validate;
 
 /// User role enum matching database user_role type
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, sqlx::Type)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, sqlx::Type, ToSchema)]
 #[sqlx(type_name = "user_role", rename_all = "lowercase")]
 #[serde(rename_all = "lowercase")]
 pub enum UserRole {
@@ -25,6 +26,15 @@ impl UserRole {
         matches!(self, UserRole::Superadmin)
     }
 
+    /// Database / API value
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UserRole::User => "user",
+            UserRole::Admin => "admin",
+            UserRole::Superadmin => "superadmin",
+        }
+    }
+
     /// Get role display name
     pub fn display_name(&self) -> &'static str {
         match self {
@@ -57,7 +67,7 @@ pub struct User {
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct CreateUserRequest {
     #[validate(email(message = "Invalid email format"))]
     pub email: String,
@@ -74,7 +84,7 @@ pub struct CreateUserRequest {
     pub license_number: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct LoginRequest {
     #[validate(email(message = "Invalid email format"))]
     pub email: String,
@@ -82,7 +92,7 @@ pub struct LoginRequest {
     pub password: String,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, ToSchema)]
 pub struct UserResponse {
     pub id: Uuid,
     pub email: String,
@@ -113,7 +123,7 @@ impl From<User> for UserResponse {
     }
 }
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct UpdateUserRequest {
     #[validate(length(min = 2, message = "Company name must be at least 2 characters"))]
     pub company_name: Option<String>,
@@ -124,4 +134,7 @@ pub struct UpdateUserRequest {
     pub address: Option<String>,
     #[validate(length(max = 100, message = "License number too long"))]
     pub license_number: Option<String>,
+    /// ISO 3166-1 alpha-2 country code (e.g. "US")
+    #[validate(length(equal = 2, message = "Country code must be 2 letters"))]
+    pub country_code: Option<String>,
 }
\ No newline at end of file
@@ -0,0 +1,202 @@
+/// Admin account suspensions and listing takedowns
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Longest timed suspension; longer ones should be indefinite
+pub const MAX_SUSPENSION_DAYS: i64 = 365;
+
+/// Most listings taken down or restored in one request
+pub const MAX_TAKEDOWN_BATCH: usize = 500;
+
+const MAX_REASON_LENGTH: usize = 1000;
+
+/// Postgres NOTIFY channel the users trigger publishes session revocations on
+pub const SESSION_REVOCATION_CHANNEL: &str = "atlas_session_revocations";
+
+// ============================================================================
+// Database Models
+// ============================================================================
+
+/// NOTIFY payload written when a user's sessions are revoked: tokens issued at or
+/// before `revoked_at` (unix seconds) are rejected
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+pub struct SessionRevocationNotice {
+    pub user_id: Uuid,
+    pub revoked_at: i64,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct UserSuspension {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub reason: String,
+    pub suspended_by: Option<Uuid>,
+    pub suspended_at: DateTime<Utc>,
+    /// None for indefinite suspensions
+    pub ends_at: Option<DateTime<Utc>>,
+    pub lifted_at: Option<DateTime<Utc>>,
+    /// None when lifted automatically at `ends_at`
+    pub lifted_by: Option<Uuid>,
+    pub lift_notes: Option<String>,
+}
+
+impl UserSuspension {
+    pub fn is_active(&self) -> bool {
+        self.lifted_at.is_none()
+    }
+}
+
+// ============================================================================
+// Request Models
+// ============================================================================
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SuspendUserRequest {
+    pub reason: String,
+    /// Reinstated automatically after this many hours; indefinite when omitted
+    pub duration_hours: Option<i64>,
+}
+
+impl SuspendUserRequest {
+    /// Trimmed reason and the end of the suspension, if timed
+    pub fn validate(&self, now: DateTime<Utc>) -> Result<(String, Option<DateTime<Utc>>), String> {
+        let reason = validate_reason(&self.reason)?;
+        let ends_at = match self.duration_hours {
+            None => None,
+            Some(hours) if hours < 1 || hours > MAX_SUSPENSION_DAYS * 24 => {
+                return Err(format!(
+                    "duration_hours must be between 1 and {}; omit it to suspend indefinitely",
+                    MAX_SUSPENSION_DAYS * 24
+                ))
+            }
+            Some(hours) => Some(now + Duration::hours(hours)),
+        };
+        Ok((reason, ends_at))
+    }
+}
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct ReinstateUserRequest {
+    pub notes: Option<String>,
+}
+
+/// Take down listings by id, every listing of one company, or both
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ListingTakedownRequest {
+    #[serde(default)]
+    pub inventory_ids: Vec<Uuid>,
+    pub user_id: Option<Uuid>,
+    pub reason: String,
+}
+
+impl ListingTakedownRequest {
+    /// The trimmed reason
+    pub fn validate(&self) -> Result<String, String> {
+        if self.inventory_ids.is_empty() && self.user_id.is_none() {
+            return Err("Provide inventory_ids, user_id or both".to_string());
+        }
+        validate_batch(&self.inventory_ids)?;
+        validate_reason(&self.reason)
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ListingRestoreRequest {
+    pub inventory_ids: Vec<Uuid>,
+}
+
+impl ListingRestoreRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.inventory_ids.is_empty() {
+            return Err("inventory_ids must not be empty".to_string());
+        }
+        validate_batch(&self.inventory_ids)
+    }
+}
+
+// ============================================================================
+// Response Models
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ListingTakedownResult {
+    /// Listings hidden by this request; listings already taken down are skipped
+    pub taken_down: Vec<Uuid>,
+    pub owners_notified: usize,
+}
+
+fn validate_reason(reason: &str) -> Result<String, String> {
+    let reason = reason.trim();
+    if reason.is_empty() {
+        return Err("reason is required".to_string());
+    }
+    if reason.len() > MAX_REASON_LENGTH {
+        return Err(format!("reason must be at most {} characters", MAX_REASON_LENGTH));
+    }
+    Ok(reason.to_string())
+}
+
+fn validate_batch(inventory_ids: &[Uuid]) -> Result<(), String> {
+    if inventory_ids.len() > MAX_TAKEDOWN_BATCH {
+        return Err(format!("At most {} listings per request", MAX_TAKEDOWN_BATCH));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suspension_duration() {
+        let now = Utc::now();
+        let timed = SuspendUserRequest { reason: "  Counterfeit listings ".to_string(), duration_hours: Some(72) };
+        assert_eq!(
+            timed.validate(now),
+            Ok(("Counterfeit listings".to_string(), Some(now + Duration::hours(72))))
+        );
+
+        let indefinite = SuspendUserRequest { reason: "Fraud".to_string(), duration_hours: None };
+        assert_eq!(indefinite.validate(now), Ok(("Fraud".to_string(), None)));
+
+        let zero = SuspendUserRequest { reason: "Fraud".to_string(), duration_hours: Some(0) };
+        assert!(zero.validate(now).is_err());
+        let too_long = SuspendUserRequest { reason: "Fraud".to_string(), duration_hours: Some(MAX_SUSPENSION_DAYS * 24 + 1) };
+        assert!(too_long.validate(now).is_err());
+        let blank = SuspendUserRequest { reason: "   ".to_string(), duration_hours: None };
+        assert!(blank.validate(now).is_err());
+    }
+
+    #[test]
+    fn test_takedown_request_validation() {
+        let by_user = ListingTakedownRequest { inventory_ids: vec![], user_id: Some(Uuid::new_v4()), reason: "Recall".to_string() };
+        assert_eq!(by_user.validate(), Ok("Recall".to_string()));
+
+        let empty = ListingTakedownRequest { inventory_ids: vec![], user_id: None, reason: "Recall".to_string() };
+        assert!(empty.validate().is_err());
+
+        let oversized = ListingTakedownRequest {
+            inventory_ids: (0..=MAX_TAKEDOWN_BATCH as u128).map(Uuid::from_u128).collect(),
+            user_id: None,
+            reason: "Recall".to_string(),
+        };
+        assert!(oversized.validate().is_err());
+
+        assert!(ListingRestoreRequest { inventory_ids: vec![] }.validate().is_err());
+    }
+
+    #[test]
+    fn test_session_revocation_notice_parses_trigger_payload() {
+        let user_id = Uuid::new_v4();
+        let payload = format!(r#"{{"user_id" : "{}", "revoked_at" : 1760000000}}"#, user_id);
+        assert_eq!(
+            serde_json::from_str::<SessionRevocationNotice>(&payload).unwrap(),
+            SessionRevocationNotice { user_id, revoked_at: 1_760_000_000 }
+        );
+        assert!(serde_json::from_str::<SessionRevocationNotice>(r#"{"user_id": "nope"}"#).is_err());
+    }
+}
@@ -0,0 +1,209 @@
+/// Verification queue decisions: bulk approval and rejection of applicants
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use sqlx::FromRow;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+use super::license::BusinessLicense;
+
+/// Most applicants decided in one request
+pub const MAX_DECISION_BATCH: usize = 100;
+
+const MAX_REASON_LENGTH: usize = 1000;
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum VerificationDecisionKind {
+    Approve,
+    Reject,
+}
+
+impl VerificationDecisionKind {
+    /// Stored value in `verification_decisions.decision`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VerificationDecisionKind::Approve => "approved",
+            VerificationDecisionKind::Reject => "rejected",
+        }
+    }
+}
+
+// ============================================================================
+// Database Models
+// ============================================================================
+
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct VerificationDecision {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub decision: String,
+    pub reason: Option<String>,
+    /// None for automatic rejections
+    pub decided_by: Option<Uuid>,
+    pub automatic: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A license attached to a pending applicant, for review next to the application
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct QueueLicense {
+    #[serde(flatten)]
+    pub license: BusinessLicense,
+    /// Where admins download the scanned document
+    pub document_url: String,
+}
+
+impl From<BusinessLicense> for QueueLicense {
+    fn from(license: BusinessLicense) -> Self {
+        Self {
+            document_url: format!("/api/licenses/{}/document", license.id),
+            license,
+        }
+    }
+}
+
+// ============================================================================
+// Request Models
+// ============================================================================
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct VerificationDecisionRequest {
+    pub user_id: Uuid,
+    pub decision: VerificationDecisionKind,
+    /// Required when rejecting; sent to the applicant
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BulkVerificationRequest {
+    pub decisions: Vec<VerificationDecisionRequest>,
+}
+
+impl BulkVerificationRequest {
+    /// The decisions with trimmed reasons, or the first problem found
+    pub fn validate(&self) -> Result<Vec<(Uuid, VerificationDecisionKind, Option<String>)>, String> {
+        if self.decisions.is_empty() {
+            return Err("decisions must not be empty".to_string());
+        }
+        if self.decisions.len() > MAX_DECISION_BATCH {
+            return Err(format!("At most {} decisions per request", MAX_DECISION_BATCH));
+        }
+
+        let mut seen = HashSet::new();
+        let mut decisions = Vec::with_capacity(self.decisions.len());
+        for request in &self.decisions {
+            if !seen.insert(request.user_id) {
+                return Err(format!("User {} appears more than once", request.user_id));
+            }
+
+            let reason = request.reason.as_deref().map(str::trim).filter(|reason| !reason.is_empty());
+            if request.decision == VerificationDecisionKind::Reject && reason.is_none() {
+                return Err(format!("A reason is required to reject user {}", request.user_id));
+            }
+            if reason.is_some_and(|reason| reason.len() > MAX_REASON_LENGTH) {
+                return Err(format!("reason must be at most {} characters", MAX_REASON_LENGTH));
+            }
+
+            decisions.push((request.user_id, request.decision, reason.map(str::to_string)));
+        }
+        Ok(decisions)
+    }
+}
+
+// ============================================================================
+// Response Models
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum VerificationOutcomeStatus {
+    Approved,
+    Rejected,
+    /// Not pending (already decided, verified, or not an applicant)
+    Skipped,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct VerificationOutcome {
+    pub user_id: Uuid,
+    pub status: VerificationOutcomeStatus,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct BulkVerificationResult {
+    pub approved: usize,
+    pub rejected: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    pub outcomes: Vec<VerificationOutcome>,
+}
+
+impl BulkVerificationResult {
+    pub fn push(&mut self, user_id: Uuid, status: VerificationOutcomeStatus, message: Option<String>) {
+        match status {
+            VerificationOutcomeStatus::Approved => self.approved += 1,
+            VerificationOutcomeStatus::Rejected => self.rejected += 1,
+            VerificationOutcomeStatus::Skipped => self.skipped += 1,
+            VerificationOutcomeStatus::Failed => self.failed += 1,
+        }
+        self.outcomes.push(VerificationOutcome { user_id, status, message });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decision(user_id: Uuid, decision: VerificationDecisionKind, reason: Option<&str>) -> VerificationDecisionRequest {
+        VerificationDecisionRequest { user_id, decision, reason: reason.map(str::to_string) }
+    }
+
+    #[test]
+    fn test_bulk_request_validation() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let request = BulkVerificationRequest {
+            decisions: vec![
+                decision(a, VerificationDecisionKind::Approve, Some("  ")),
+                decision(b, VerificationDecisionKind::Reject, Some(" License number not found with the state board ")),
+            ],
+        };
+        assert_eq!(
+            request.validate(),
+            Ok(vec![
+                (a, VerificationDecisionKind::Approve, None),
+                (b, VerificationDecisionKind::Reject, Some("License number not found with the state board".to_string())),
+            ])
+        );
+
+        assert!(BulkVerificationRequest { decisions: vec![] }.validate().is_err());
+        let no_reason = BulkVerificationRequest { decisions: vec![decision(a, VerificationDecisionKind::Reject, Some(" "))] };
+        assert!(no_reason.validate().is_err());
+        let duplicate = BulkVerificationRequest {
+            decisions: vec![
+                decision(a, VerificationDecisionKind::Approve, None),
+                decision(a, VerificationDecisionKind::Reject, Some("Duplicate")),
+            ],
+        };
+        assert!(duplicate.validate().is_err());
+        let oversized = BulkVerificationRequest {
+            decisions: (0..=MAX_DECISION_BATCH as u128)
+                .map(|i| decision(Uuid::from_u128(i), VerificationDecisionKind::Approve, None))
+                .collect(),
+        };
+        assert!(oversized.validate().is_err());
+    }
+
+    #[test]
+    fn test_result_counts() {
+        let mut result = BulkVerificationResult::default();
+        result.push(Uuid::new_v4(), VerificationOutcomeStatus::Approved, None);
+        result.push(Uuid::new_v4(), VerificationOutcomeStatus::Skipped, Some("Already verified".to_string()));
+        assert_eq!((result.approved, result.rejected, result.skipped, result.failed), (1, 0, 1, 0));
+        assert_eq!(result.outcomes.len(), 2);
+    }
+}
@@ -0,0 +1,207 @@
+/// Outbound webhook models: user-registered endpoints, platform events and deliveries
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Failed deliveries in a row after which an endpoint is disabled
+pub const MAX_CONSECUTIVE_FAILURES: i32 = 50;
+
+/// Stored size of an endpoint's response body
+pub const RESPONSE_BODY_LIMIT: usize = 2048;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, ToSchema)]
+pub enum WebhookEventType {
+    #[serde(rename = "inquiry.created")]
+    InquiryCreated,
+    #[serde(rename = "transaction.completed")]
+    TransactionCompleted,
+    #[serde(rename = "inventory.low_stock")]
+    InventoryLowStock,
+    #[serde(rename = "sync.finished")]
+    SyncFinished,
+}
+
+impl WebhookEventType {
+    pub const ALL: [WebhookEventType; 4] = [
+        WebhookEventType::InquiryCreated,
+        WebhookEventType::TransactionCompleted,
+        WebhookEventType::InventoryLowStock,
+        WebhookEventType::SyncFinished,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEventType::InquiryCreated => "inquiry.created",
+            WebhookEventType::TransactionCompleted => "transaction.completed",
+            WebhookEventType::InventoryLowStock => "inventory.low_stock",
+            WebhookEventType::SyncFinished => "sync.finished",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            WebhookEventType::InquiryCreated => "A buyer sent an inquiry about one of your listings",
+            WebhookEventType::TransactionCompleted => "A transaction you are a party to was completed",
+            WebhookEventType::InventoryLowStock => "One of your listings fell below your low-stock threshold",
+            WebhookEventType::SyncFinished => "A regulatory catalog sync (OpenFDA, EMA, ...) finished",
+        }
+    }
+}
+
+impl std::fmt::Display for WebhookEventType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+// ============================================================================
+// DATABASE MODELS
+// ============================================================================
+
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct WebhookEndpoint {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub url: String,
+    pub description: Option<String>,
+    #[serde(skip_serializing)]
+    pub secret_encrypted: String,
+    pub event_types: Vec<String>,
+    pub is_active: bool,
+    pub consecutive_failures: i32,
+    pub disabled_reason: Option<String>,
+    pub last_delivery_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, ToSchema)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub endpoint_id: Uuid,
+    pub event_id: Uuid,
+    pub event_type: String,
+    pub payload: serde_json::Value,
+    pub status: String,
+    pub attempt_count: i32,
+    pub next_attempt_at: Option<DateTime<Utc>>,
+    pub last_attempt_at: Option<DateTime<Utc>>,
+    pub response_status: Option<i32>,
+    pub response_body: Option<String>,
+    pub error_message: Option<String>,
+    pub duration_ms: Option<i32>,
+    pub replay_of: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+// ============================================================================
+// API REQUEST MODELS
+// ============================================================================
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateWebhookEndpointRequest {
+    pub url: String,
+    pub description: Option<String>,
+    pub event_types: Vec<WebhookEventType>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateWebhookEndpointRequest {
+    pub url: Option<String>,
+    pub description: Option<String>,
+    pub event_types: Option<Vec<WebhookEventType>>,
+    /// Re-enabling an endpoint also resets its failure count
+    pub is_active: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct WebhookDeliveriesQuery {
+    pub endpoint_id: Option<Uuid>,
+    pub status: Option<String>,
+    pub event_type: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+// ============================================================================
+// API RESPONSE MODELS
+// ============================================================================
+
+/// Returned on creation and secret rotation only - the secret is never shown again
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WebhookEndpointWithSecret {
+    #[serde(flatten)]
+    pub endpoint: WebhookEndpoint,
+    pub secret: String,
+}
+
+/// Body POSTed to endpoints
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookEventPayload {
+    pub event_id: Uuid,
+    pub event_type: WebhookEventType,
+    pub created_at: DateTime<Utc>,
+    pub data: serde_json::Value,
+}
+
+// ============================================================================
+// DELIVERY SCHEDULE
+// ============================================================================
+
+/// Wait before retry number `attempt` (1 = first retry); `None` once retries are exhausted.
+/// Roughly 1m, 5m, 30m, 2h, 6h, 12h, 24h: an endpoint is retried for about two days.
+pub fn retry_delay(attempt: i32) -> Option<Duration> {
+    match attempt {
+        1 => Some(Duration::minutes(1)),
+        2 => Some(Duration::minutes(5)),
+        3 => Some(Duration::minutes(30)),
+        4 => Some(Duration::hours(2)),
+        5 => Some(Duration::hours(6)),
+        6 => Some(Duration::hours(12)),
+        7 => Some(Duration::hours(24)),
+        _ => None,
+    }
+}
+
+/// Whether a delivery that got `status` (None: no response) should be retried
+pub fn is_retryable(status: Option<u16>) -> bool {
+    match status {
+        None => true,
+        // Timeouts and rate limits are transient; other client errors are not
+        Some(408) | Some(429) => true,
+        Some(s) => s >= 500,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_type_names() {
+        for event_type in WebhookEventType::ALL {
+            let json = serde_json::to_value(event_type).unwrap();
+            assert_eq!(json, event_type.as_str());
+            assert_eq!(serde_json::from_value::<WebhookEventType>(json).unwrap(), event_type);
+        }
+        assert!(serde_json::from_value::<WebhookEventType>(serde_json::json!("inquiry.deleted")).is_err());
+    }
+
+    #[test]
+    fn test_retry_schedule() {
+        assert_eq!(retry_delay(1), Some(Duration::minutes(1)));
+        assert!((1..7).all(|a| retry_delay(a) < retry_delay(a + 1)));
+        assert_eq!(retry_delay(8), None);
+
+        assert!(is_retryable(None));
+        assert!(is_retryable(Some(503)));
+        assert!(is_retryable(Some(429)));
+        assert!(!is_retryable(Some(404)));
+        assert!(!is_retryable(Some(400)));
+    }
+}
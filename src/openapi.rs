@@ -0,0 +1,791 @@
+// ============================================================================
+// OpenAPI Specification - Generated From Handler Annotations
+// ============================================================================
+//
+// 📖 Every handler carries a `#[utoipa::path]` attribute (method, path,
+// parameters, request body, responses) and the request/response types derive
+// `ToSchema` (query structs `IntoParams`), so the spec is built from the same
+// types the handlers use and can't drift from them. On top of the annotations:
+//
+// - security: a bearer JWT or the `auth_token` cookie by default; routes in
+//   `PUBLIC_ROUTES` need neither, and admin routes name the role they need
+//   (both taken from the route policy audit of main.rs)
+// - every operation documents the RFC 7807 problem document of `AppError`
+//   (`application/problem+json`, see middleware::problem_details) as its
+//   default response
+// - routes served by another route's handler (`SHARED_ROUTES`) get its operation
+//
+// Served at GET /api/openapi.json (OpenAPI 3.1) with Swagger UI at /api/docs.
+// tests/openapi.rs fails when a route in main.rs has no operation, or an
+// operation no route, so a new handler can't ship undocumented.
+//
+// ============================================================================
+
+use axum::Json;
+use once_cell::sync::Lazy;
+use utoipa::openapi::path::{Operation, PathItem};
+use utoipa::openapi::response::ResponseBuilder;
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::openapi::{ContentBuilder, Ref, RefOr};
+use utoipa::{Modify, OpenApi, ToSchema};
+
+use crate::handlers;
+use crate::middleware::route_policy::{route_policy_report, RoutePolicy, RoutePolicyReport};
+
+/// Routes served by the handler of another documented route: (route, documented route)
+pub const SHARED_ROUTES: &[(&str, &str)] = &[
+    ("/api/admin/health/ready", "/api/admin/health"),
+    ("/api/public/inventory/search", "/api/marketplace/search"),
+];
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Atlas Pharma API",
+        description = "B2B pharmaceutical marketplace: inventory, inquiries and transactions, regulatory catalogs and documents, compliance and ERP integration",
+    ),
+    paths(
+        handlers::auth::register,
+        handlers::auth::login,
+        handlers::auth::refresh_token,
+        handlers::auth::logout,
+        handlers::auth::get_profile,
+        handlers::auth::get_limits,
+        handlers::auth::update_profile,
+        handlers::auth::change_password,
+        handlers::auth::delete_account,
+        handlers::oauth::get_oauth_providers,
+        handlers::oauth::oauth_start,
+        handlers::oauth::oauth_callback,
+        handlers::oauth::oauth_link_start,
+        handlers::oauth::oauth_unlink,
+        handlers::admin::health_check,
+        handlers::admin::liveness_check,
+        handlers::admin::list_users,
+        handlers::admin::get_user,
+        handlers::admin::verify_user,
+        handlers::admin::suspend_user,
+        handlers::admin::reinstate_user,
+        handlers::admin::get_user_suspensions,
+        handlers::admin::take_down_listings,
+        handlers::admin::restore_listings,
+        handlers::admin::get_verification_queue,
+        handlers::admin::decide_verifications,
+        handlers::admin::get_admin_stats,
+        handlers::admin_analytics::get_funnel,
+        handlers::admin_analytics::get_active_users,
+        handlers::admin_analytics::get_gmv,
+        handlers::admin::get_route_policies,
+        handlers::admin::get_audit_logs,
+        handlers::audit_chain::verify_chain,
+        handlers::audit_chain::list_anchors,
+        handlers::audit_chain::create_anchor,
+        handlers::background_jobs::list_jobs,
+        handlers::background_jobs::get_job,
+        handlers::background_jobs::retry_job,
+        handlers::background_jobs::cancel_job,
+        handlers::admin_ops::get_ops_dashboard,
+        handlers::admin_security::get_api_usage_analytics,
+        handlers::admin_security::get_user_quotas,
+        handlers::admin_security::get_encryption_status,
+        handlers::admin_security::get_reencryption_status,
+        handlers::admin_security::get_metrics_summary,
+        handlers::admin_security::get_rate_limit_status,
+        handlers::sync_sources::get_sync_dashboard,
+        handlers::sync_sources::list_sources,
+        handlers::sync_sources::update_source,
+        handlers::sync_sources::pause_scheduler,
+        handlers::sync_sources::resume_scheduler,
+        handlers::semantic_search::start_embedding_backfill,
+        handlers::semantic_search::list_embedding_jobs,
+        handlers::admin::get_ai_cache_stats,
+        handlers::admin::purge_ai_cache,
+        handlers::admin::get_query_cache_stats,
+        handlers::admin::purge_query_cache,
+        handlers::admin::get_search_engine_status,
+        handlers::admin::reindex_search_engine,
+        handlers::ai_quota::admin_get_usage,
+        handlers::billing::admin_get_usage,
+        handlers::anomalies::list_anomalies,
+        handlers::anomalies::run_anomaly_detection,
+        handlers::anomalies::get_anomaly,
+        handlers::anomalies::confirm_anomaly,
+        handlers::anomalies::dismiss_anomaly,
+        handlers::announcements::list_announcements,
+        handlers::announcements::create_announcement,
+        handlers::announcements::withdraw_announcement,
+        handlers::legal_holds::list_legal_holds,
+        handlers::legal_holds::create_legal_hold,
+        handlers::legal_holds::get_legal_hold,
+        handlers::legal_holds::release_legal_hold,
+        handlers::licenses::list_licenses,
+        handlers::licenses::review_license,
+        handlers::sanctions::list_screenings,
+        handlers::sanctions::get_screening,
+        handlers::sanctions::review_screening,
+        handlers::sanctions::screen_user,
+        handlers::sanctions::get_list_status,
+        handlers::sanctions::list_syncs,
+        handlers::sanctions::sync_lists,
+        handlers::regulatory_rules::list_rules,
+        handlers::regulatory_rules::create_rule,
+        handlers::regulatory_rules::list_evaluations,
+        handlers::regulatory_rules::get_rule,
+        handlers::regulatory_rules::update_rule,
+        handlers::regulatory_rules::delete_rule,
+        handlers::admin::change_user_role,
+        handlers::admin::delete_user,
+        handlers::admin_security::update_user_quota,
+        handlers::admin_security::rotate_encryption_key,
+        handlers::admin_security::start_reencryption,
+        handlers::ai_quota::admin_set_tier,
+        handlers::ai_quota::admin_set_override,
+        handlers::ai_quota::admin_remove_override,
+        handlers::billing::admin_assign_plan,
+        handlers::billing::admin_generate_statements,
+        handlers::settings::list_settings,
+        handlers::settings::update_setting,
+        handlers::settings::reset_setting,
+        handlers::settings::get_setting_history,
+        handlers::maintenance::get_maintenance_status,
+        handlers::maintenance::update_maintenance,
+        handlers::payload_captures::list_captures,
+        handlers::payload_captures::get_capture,
+        handlers::payload_captures::list_capture_rules,
+        handlers::payload_captures::create_capture_rule,
+        handlers::payload_captures::revoke_capture_rule,
+        handlers::data_retention::list_policies,
+        handlers::data_retention::update_policy,
+        handlers::data_retention::list_purge_runs,
+        handlers::data_retention::start_purge_run,
+        handlers::data_retention::get_purge_run,
+        handlers::mfa::get_mfa_status,
+        handlers::mfa::start_enrollment,
+        handlers::mfa::complete_enrollment,
+        handlers::mfa::verify_mfa,
+        handlers::mfa::disable_mfa,
+        handlers::mfa::get_trusted_devices,
+        handlers::mfa::revoke_trusted_device,
+        handlers::pharmaceutical::create_pharmaceutical,
+        handlers::pharmaceutical::get_pharmaceutical,
+        handlers::pharmaceutical::search_pharmaceuticals,
+        handlers::pharmaceutical::get_manufacturers,
+        handlers::pharmaceutical::get_categories,
+        handlers::pharmaceutical::get_ingredients,
+        handlers::pharmaceutical::set_ingredients,
+        handlers::product_images::upload_image,
+        handlers::product_images::delete_image,
+        handlers::product_images::moderate_image,
+        handlers::product_images::set_primary_image,
+        handlers::product_images::get_moderation_queue,
+        handlers::product_images::list_images,
+        handlers::product_images::get_image_file,
+        handlers::product_images::get_image_thumbnail,
+        handlers::inventory::add_inventory,
+        handlers::inventory::get_inventory,
+        handlers::inventory::get_user_inventory,
+        handlers::inventory::update_inventory,
+        handlers::inventory::delete_inventory,
+        handlers::inventory::search_marketplace,
+        handlers::marketplace::create_inquiry,
+        handlers::marketplace::get_inquiry,
+        handlers::marketplace::get_buyer_inquiries,
+        handlers::marketplace::get_seller_inquiries,
+        handlers::marketplace::update_inquiry_status,
+        handlers::inquiry_messages::get_inquiry_messages,
+        handlers::inquiry_messages::create_message,
+        handlers::inquiry_messages::get_message_count,
+        handlers::regulatory_rules::get_inquiry_requirements,
+        handlers::regulatory_rules::check_requirements,
+        handlers::marketplace::create_transaction,
+        handlers::marketplace::get_transaction,
+        handlers::marketplace::get_user_transactions,
+        handlers::marketplace::complete_transaction,
+        handlers::marketplace::cancel_transaction,
+        handlers::temperature_excursions::list_temperature_logs,
+        handlers::temperature_excursions::upload_temperature_log,
+        handlers::temperature_excursions::get_excursion_report,
+        handlers::temperature_excursions::open_excursion_dispute,
+        handlers::temperature_excursions::list_transaction_disputes,
+        handlers::temperature_excursions::resolve_dispute,
+        handlers::temperature_excursions::get_temperature_summary,
+        handlers::licenses::get_license_registry,
+        handlers::licenses::upload_license,
+        handlers::licenses::get_license,
+        handlers::licenses::delete_license,
+        handlers::licenses::get_license_document,
+        handlers::inventory::get_expiry_alerts,
+        handlers::openfda::search_catalog,
+        handlers::openfda::get_by_ndc,
+        handlers::openfda::get_ndc_history,
+        handlers::openfda::get_stats,
+        handlers::openfda::export_catalog,
+        handlers::openfda::get_manufacturers,
+        handlers::openfda::health_check,
+        handlers::openfda::check_refresh_status,
+        handlers::openfda::search_devices,
+        handlers::openfda::get_device_by_di,
+        handlers::openfda::trigger_device_sync,
+        handlers::openfda::search_recalls,
+        handlers::openfda::get_recalls_by_ndc,
+        handlers::openfda::trigger_recall_sync,
+        handlers::openfda::get_recall,
+        handlers::openfda::trigger_sync,
+        handlers::openfda::get_active_sync,
+        handlers::openfda::get_sync_logs,
+        handlers::openfda::get_sync_progress,
+        handlers::openfda::cancel_sync,
+        handlers::openfda::resume_sync,
+        handlers::openfda::cleanup_sync_logs,
+        handlers::ema::search_catalog,
+        handlers::ema::get_by_eu_number,
+        handlers::ema::get_documents,
+        handlers::ema::search_documents,
+        handlers::ema::get_stats,
+        handlers::ema::export_catalog,
+        handlers::ema::trigger_sync,
+        handlers::ema::trigger_background_sync,
+        handlers::ema::get_active_sync,
+        handlers::ema::get_sync_logs,
+        handlers::ema::get_sync_progress,
+        handlers::ema::cancel_sync,
+        handlers::ema::check_refresh_status,
+        handlers::ema::get_config_info,
+        handlers::ema::cleanup_sync_logs,
+        handlers::ema::health_check,
+        handlers::ai_import::upload_and_analyze,
+        handlers::ai_import::list_sessions,
+        handlers::ai_import::get_session,
+        handlers::ai_import::start_import,
+        handlers::ai_import::preview_rows,
+        handlers::ai_import::update_row_decisions,
+        handlers::ai_import::rollback_import,
+        handlers::ai_import::get_session_rows,
+        handlers::ai_import::get_extraction,
+        handlers::ai_import::review_extraction,
+        handlers::ai_import::apply_extraction,
+        handlers::ai_import::get_user_quota,
+        handlers::ai_import::list_remembered_mappings,
+        handlers::ai_import::delete_remembered_mapping,
+        handlers::nl_query::execute_query,
+        handlers::nl_query::get_session,
+        handlers::nl_query::get_history,
+        handlers::nl_query::save_favorite,
+        handlers::nl_query::get_favorites,
+        handlers::nl_query::get_quota,
+        handlers::nl_query::export_session,
+        handlers::nl_query::create_report_schedule,
+        handlers::nl_query::list_report_schedules,
+        handlers::nl_query::update_report_schedule,
+        handlers::nl_query::delete_report_schedule,
+        handlers::nl_query::list_report_files,
+        handlers::nl_query::download_report_file,
+        handlers::inquiry_assistant::generate_suggestion,
+        handlers::inquiry_assistant::get_suggestion,
+        handlers::inquiry_assistant::accept_suggestion,
+        handlers::inquiry_assistant::reject_suggestion,
+        handlers::inquiry_assistant::get_assistant_thread,
+        handlers::inquiry_assistant::clear_assistant_thread,
+        handlers::inquiry_assistant::get_inquiry_suggestions,
+        handlers::inquiry_assistant::get_quota,
+        handlers::inquiry_assistant::get_language_preferences,
+        handlers::inquiry_assistant::update_language_preferences,
+        handlers::pricing_suggestions::generate_suggestion,
+        handlers::pricing_suggestions::list_suggestions,
+        handlers::pricing_suggestions::get_suggestion,
+        handlers::pricing_suggestions::get_quota,
+        handlers::ai_quota::get_usage,
+        handlers::ai_quota::list_tiers,
+        handlers::ai_quota::purchase_topup,
+        handlers::ai_quota::list_topups,
+        handlers::billing::get_usage,
+        handlers::billing::get_account,
+        handlers::billing::list_plans,
+        handlers::billing::list_statements,
+        handlers::billing::get_statement,
+        handlers::alerts::get_notifications,
+        handlers::alerts::get_unread_count,
+        handlers::alerts::mark_notification_read,
+        handlers::alerts::mark_all_read,
+        handlers::alerts::get_archived_notifications,
+        handlers::alerts::bulk_dismiss_notifications,
+        handlers::alerts::dismiss_notification,
+        handlers::announcements::get_announcement_feed,
+        handlers::announcements::mark_announcement_read,
+        handlers::alerts::stream_notifications,
+        handlers::alerts::get_preferences,
+        handlers::alerts::update_preferences,
+        handlers::alerts::get_channels,
+        handlers::alerts::create_channel,
+        handlers::alerts::get_channel,
+        handlers::alerts::update_channel,
+        handlers::alerts::delete_channel,
+        handlers::alerts::start_channel_authorization,
+        handlers::alerts::complete_channel_authorization,
+        handlers::alerts::test_channel,
+        handlers::alerts::get_watchlists,
+        handlers::alerts::create_watchlist,
+        handlers::alerts::get_watchlist,
+        handlers::alerts::update_watchlist,
+        handlers::alerts::delete_watchlist,
+        handlers::alerts::get_watchlist_matches,
+        handlers::alerts::pause_watchlist,
+        handlers::alerts::resume_watchlist,
+        handlers::alerts::get_watchlist_triggers,
+        handlers::alerts::create_watchlist_trigger,
+        handlers::alerts::update_watchlist_trigger,
+        handlers::alerts::delete_watchlist_trigger,
+        handlers::catalog_subscriptions::list_subscriptions,
+        handlers::catalog_subscriptions::create_subscription,
+        handlers::catalog_subscriptions::get_subscription,
+        handlers::catalog_subscriptions::update_subscription,
+        handlers::catalog_subscriptions::delete_subscription,
+        handlers::catalog_subscriptions::get_subscription_events,
+        handlers::webhooks::list_event_types,
+        handlers::webhooks::list_endpoints,
+        handlers::webhooks::create_endpoint,
+        handlers::webhooks::get_endpoint,
+        handlers::webhooks::update_endpoint,
+        handlers::webhooks::delete_endpoint,
+        handlers::webhooks::rotate_endpoint_secret,
+        handlers::webhooks::list_deliveries,
+        handlers::webhooks::get_delivery,
+        handlers::webhooks::replay_delivery,
+        handlers::regulator_catalogs::list_sources,
+        handlers::regulator_catalogs::federated_search,
+        handlers::regulator_catalogs::search_catalog,
+        handlers::regulator_catalogs::get_product,
+        handlers::regulator_catalogs::get_stats,
+        handlers::regulator_catalogs::trigger_sync,
+        handlers::regulator_catalogs::get_sync_logs,
+        handlers::semantic_search::semantic_search,
+        handlers::product_verification::verification_lookup,
+        handlers::product_verification::register_serial_numbers,
+        handlers::product_verification::list_verification_requests,
+        handlers::regulatory_documents::generate_document,
+        handlers::regulatory_documents::list_documents,
+        handlers::document_batches::list_batches,
+        handlers::document_batches::create_batch,
+        handlers::document_batches::get_batch,
+        handlers::document_batches::download_batch,
+        handlers::regulatory_documents::get_document,
+        handlers::regulatory_documents::approve_document,
+        handlers::regulatory_documents::set_document_signers,
+        handlers::regulatory_documents::get_document_signatures,
+        handlers::regulatory_documents::sign_document,
+        handlers::regulatory_documents::revise_document,
+        handlers::regulatory_documents::obsolete_document,
+        handlers::regulatory_documents::get_document_versions,
+        handlers::regulatory_documents::get_document_diff,
+        handlers::regulatory_documents::verify_document,
+        handlers::regulatory_documents::get_audit_trail,
+        handlers::dscsa::get_transaction_dscsa_documents,
+        handlers::dscsa::generate_transaction_dscsa_documents,
+        handlers::regulatory_documents::get_knowledge_base_stats,
+        handlers::regulatory_knowledge_base::list_knowledge_entries,
+        handlers::regulatory_knowledge_base::create_knowledge_entry,
+        handlers::regulatory_knowledge_base::import_knowledge_document,
+        handlers::regulatory_knowledge_base::list_knowledge_sources,
+        handlers::regulatory_knowledge_base::list_knowledge_source_versions,
+        handlers::regulatory_knowledge_base::get_knowledge_entry,
+        handlers::regulatory_knowledge_base::update_knowledge_entry,
+        handlers::regulatory_knowledge_base::delete_knowledge_entry,
+        handlers::regulatory_knowledge_base::get_knowledge_entry_history,
+        handlers::erp_integration::create_connection,
+        handlers::erp_integration::list_connections,
+        handlers::erp_integration::get_connection,
+        handlers::erp_integration::delete_connection,
+        handlers::erp_integration::test_connection,
+        handlers::erp_integration::get_connection_entities,
+        handlers::erp_integration::replace_connection_entities,
+        handlers::erp_integration::trigger_sync,
+        handlers::erp_integration::get_sync_logs,
+        handlers::erp_integration::get_mappings,
+        handlers::erp_integration::create_mapping,
+        handlers::erp_integration::update_mapping,
+        handlers::erp_integration::delete_mapping,
+        handlers::erp_integration::get_pending_mappings,
+        handlers::erp_integration::resolve_pending_mapping,
+        handlers::erp_integration::set_sandbox_mode,
+        handlers::erp_integration::get_sandbox_writes,
+        handlers::erp_integration::browse_erp_items,
+        handlers::erp_integration::list_conflicts,
+        handlers::erp_integration::resolve_conflict,
+        handlers::erp_integration::undo_conflict_resolution,
+        handlers::erp_integration::update_order_export_settings,
+        handlers::erp_integration::get_transaction_orders,
+        handlers::erp_integration::retry_transaction_orders,
+        handlers::accounting::list_accounting_connections,
+        handlers::accounting::create_accounting_connection,
+        handlers::accounting::get_accounting_connection,
+        handlers::accounting::delete_accounting_connection,
+        handlers::accounting::start_accounting_authorization,
+        handlers::accounting::complete_accounting_authorization,
+        handlers::accounting::update_accounting_settings,
+        handlers::accounting::get_transaction_invoices,
+        handlers::accounting::retry_transaction_invoices,
+        handlers::erp_integration::list_field_mapping_templates,
+        handlers::erp_integration::create_field_mapping_template,
+        handlers::erp_integration::update_field_mapping_template,
+        handlers::erp_integration::delete_field_mapping_template,
+        handlers::erp_integration::get_connection_field_mapping,
+        handlers::erp_integration::assign_connection_field_mapping,
+        handlers::erp_integration::get_column_mapping,
+        handlers::erp_integration::update_column_mapping,
+        handlers::erp_integration::preview_column_mapping,
+        handlers::erp_integration::get_file_transfers,
+        handlers::erp_ai_integration::auto_discover_mappings,
+        handlers::erp_ai_integration::get_mapping_suggestions,
+        handlers::erp_ai_integration::review_mapping_suggestion,
+        handlers::erp_ai_integration::get_mapping_status,
+        handlers::erp_ai_integration::get_sync_analysis,
+        handlers::erp_ai_integration::suggest_conflict_resolution,
+        handlers::erp_integration::netsuite_webhook,
+        handlers::erp_integration::sap_webhook,
+        handlers::edi::get_partner,
+        handlers::edi::upsert_partner,
+        handlers::edi::delete_partner,
+        handlers::edi::list_documents,
+        handlers::edi::get_document_raw,
+        handlers::edi::send_purchase_order,
+        handlers::edi::upload_interchange,
+        handlers::edi::as2_inbound,
+        handlers::data_exports::list_exports,
+        handlers::data_exports::create_export,
+        handlers::data_exports::get_export,
+        handlers::data_exports::download_export,
+        handlers::graphql::graphql,
+        handlers::fhir::capability_statement,
+        handlers::fhir::smart_configuration,
+        handlers::fhir::token,
+        handlers::fhir::search_medication,
+        handlers::fhir::read_medication,
+        handlers::fhir::search_medication_knowledge,
+        handlers::fhir::read_medication_knowledge,
+        handlers::fhir::search_inventory_item,
+        handlers::fhir::read_inventory_item,
+        handlers::fhir::list_fhir_clients,
+        handlers::fhir::create_fhir_client,
+        handlers::fhir::revoke_fhir_client,
+        handlers::batch_operations::create_batch_operation,
+        handlers::batch_operations::get_batch_operation,
+        handlers::batch_operations::get_batch_operation_results,
+        crate::middleware::metrics::metrics_handler,
+        openapi_json,
+    ),
+    components(schemas(ErrorResponse, crate::middleware::problem_details::FieldError)),
+    modifiers(&SecuritySchemes, &ErrorResponses, &SharedRoutes),
+    security(("bearer_auth" = []), ("cookie_auth" = [])),
+    tags(
+        (name = "auth", description = "Registration, sign-in, sessions and profile"),
+        (name = "oauth", description = "Sign-in with external OAuth providers"),
+        (name = "admin", description = "User verification, roles and platform health (admin)"),
+        (name = "admin_analytics", description = "Platform usage analytics (admin)"),
+        (name = "audit_chain", description = "Tamper-evident audit log verification (admin)"),
+        (name = "background_jobs", description = "Background job queue (admin)"),
+        (name = "admin_ops", description = "Operations dashboard (admin)"),
+        (name = "admin_security", description = "API usage, quotas, rate limits and encryption keys (admin)"),
+        (name = "sync_sources", description = "Regulatory catalog sync sources (admin)"),
+        (name = "semantic_search", description = "Semantic product search and its embedding index"),
+        (name = "ai_quota", description = "AI usage quotas, tiers and top-ups"),
+        (name = "billing", description = "Plans, usage metering and statements"),
+        (name = "anomalies", description = "Transaction and inventory anomaly detection (admin)"),
+        (name = "announcements", description = "Platform announcements"),
+        (name = "legal_holds", description = "Legal holds that suspend data retention (admin)"),
+        (name = "licenses", description = "Trading licenses and their verification"),
+        (name = "sanctions", description = "Sanctions list screening (admin)"),
+        (name = "regulatory_rules", description = "Compliance rules and marketplace regulatory checks"),
+        (name = "settings", description = "Runtime platform settings (admin)"),
+        (name = "maintenance", description = "Maintenance mode (admin)"),
+        (name = "payload_captures", description = "Captured integration payloads for debugging (admin)"),
+        (name = "data_retention", description = "Data retention policies and purge runs (admin)"),
+        (name = "mfa", description = "Multi-factor authentication"),
+        (name = "pharmaceutical", description = "Pharmaceutical product catalog"),
+        (name = "product_images", description = "Product images"),
+        (name = "inventory", description = "Seller inventory"),
+        (name = "marketplace", description = "Marketplace search, inquiries and transactions"),
+        (name = "inquiry_messages", description = "Messages on inquiries"),
+        (name = "temperature_excursions", description = "Cold-chain temperature excursions"),
+        (name = "openfda", description = "FDA drug catalog (openFDA)"),
+        (name = "ema", description = "EMA medicines catalog"),
+        (name = "ai_import", description = "AI-assisted inventory import from files"),
+        (name = "nl_query", description = "Natural-language queries, favorites and scheduled reports"),
+        (name = "inquiry_assistant", description = "AI reply suggestions on inquiries"),
+        (name = "pricing_suggestions", description = "AI pricing suggestions"),
+        (name = "alerts", description = "Notifications, alert preferences and channels"),
+        (name = "catalog_subscriptions", description = "Catalog change subscriptions"),
+        (name = "webhooks", description = "Outgoing webhook endpoints and deliveries"),
+        (name = "regulator_catalogs", description = "Regulator catalogs beyond FDA and EMA"),
+        (name = "product_verification", description = "Product and serial number verification"),
+        (name = "regulatory_documents", description = "Generated and signed regulatory documents"),
+        (name = "document_batches", description = "Batch generation of regulatory documents"),
+        (name = "dscsa", description = "DSCSA transaction documentation"),
+        (name = "regulatory_knowledge_base", description = "Regulatory knowledge base for AI answers"),
+        (name = "erp_integration", description = "ERP connections, sync and webhooks"),
+        (name = "accounting", description = "Accounting system connections"),
+        (name = "erp_ai_integration", description = "AI field mapping and conflict resolution for ERP sync"),
+        (name = "edi", description = "EDI trading partners, documents and AS2"),
+        (name = "data_exports", description = "Data exports and signed download links"),
+        (name = "graphql", description = "GraphQL endpoint over the catalog, inventory, marketplace and notifications"),
+        (name = "batch_operations", description = "Bulk operations run by a background job with per-operation results"),
+        (name = "fhir", description = "FHIR R4 facade (Medication, MedicationKnowledge, InventoryItem), its SMART token endpoint and client registration"),
+        (name = "metrics", description = "Prometheus metrics"),
+        (name = "api_docs", description = "This API description"),
+    ),
+)]
+pub struct ApiDoc;
+
+/// Body of every error response: an RFC 7807 problem document (see `Problem`)
+#[derive(serde::Serialize, ToSchema)]
+pub struct ErrorResponse {
+    /// `urn:atlas-pharma:problem:<code>`
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    /// Short summary of the code, in the language of Accept-Language (en, de, es, fr)
+    pub title: String,
+    /// The HTTP status code
+    pub status: u16,
+    /// Client-safe description of this occurrence, in English
+    pub detail: Option<String>,
+    /// Stable, machine-readable error code, e.g. `not_found` or `validation_failed`
+    pub code: String,
+    /// Same as the X-Request-ID response header
+    pub request_id: Option<String>,
+    /// Failed field validations (`validation_failed` only)
+    pub errors: Option<Vec<crate::middleware::problem_details::FieldError>>,
+    /// `detail`, or `title` without one; kept for clients of the older `{ error, status }` body
+    pub error: String,
+}
+
+/// A file download
+#[derive(ToSchema)]
+#[schema(value_type = String, format = Binary)]
+pub struct FileDownload(pub Vec<u8>);
+
+/// Multipart form of a product image upload
+#[derive(ToSchema)]
+pub struct ImageUploadForm {
+    /// JPEG, PNG or WebP image
+    #[schema(value_type = String, format = Binary)]
+    pub file: Vec<u8>,
+    /// At most 255 characters
+    pub alt_text: Option<String>,
+    /// "true" makes it the product's primary image
+    pub is_primary: Option<bool>,
+}
+
+/// Multipart form of a license upload
+#[derive(ToSchema)]
+pub struct LicenseUploadForm {
+    /// The license document
+    #[schema(value_type = String, format = Binary)]
+    pub file: Vec<u8>,
+    pub license_type: String,
+    pub license_number: String,
+    pub issuing_authority: String,
+    /// ISO 3166-1 alpha-2
+    pub country_code: Option<String>,
+    /// YYYY-MM-DD
+    pub issued_on: Option<String>,
+    /// YYYY-MM-DD; omit for licenses that don't expire
+    pub expires_on: Option<String>,
+}
+
+/// Multipart form of an AI import upload
+#[derive(ToSchema)]
+pub struct ImportUploadForm {
+    /// CSV, Excel, JSON, PDF or image file
+    #[schema(value_type = String, format = Binary)]
+    pub file: Vec<u8>,
+    /// At most 255 characters
+    pub supplier_name: Option<String>,
+    /// "true" analyzes the file again even if an identical one was analyzed before
+    pub force_analysis: Option<bool>,
+}
+
+/// Multipart form of a knowledge base document upload
+#[derive(ToSchema)]
+pub struct KnowledgeDocumentForm {
+    /// Text, Markdown or PDF document
+    #[schema(value_type = String, format = Binary)]
+    pub file: Vec<u8>,
+    pub document_type: String,
+    pub regulation_source: String,
+    /// At most 100 characters
+    pub regulation_section: Option<String>,
+    /// "true" replaces the chunks already imported for the same source and section
+    pub replace_existing: Option<bool>,
+    /// Target chunk size in characters
+    pub chunk_chars: Option<u32>,
+    pub notes: Option<String>,
+}
+
+/// Bearer JWT, or the `auth_token` cookie set by sign-in
+struct SecuritySchemes;
+
+impl Modify for SecuritySchemes {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).bearer_format("JWT").build()),
+        );
+        components.add_security_scheme("cookie_auth", SecurityScheme::ApiKey(ApiKey::Cookie(ApiKeyValue::new("auth_token"))));
+    }
+}
+
+/// The `AppError` body as the default response of every operation
+struct ErrorResponses;
+
+impl Modify for ErrorResponses {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let error = ResponseBuilder::new()
+            .description("Error as an RFC 7807 problem document; `status` repeats the HTTP status code")
+            .content(
+                crate::middleware::problem_details::PROBLEM_JSON,
+                ContentBuilder::new().schema(Some(Ref::from_schema_name("ErrorResponse"))).build(),
+            )
+            .build();
+        openapi
+            .components
+            .get_or_insert_with(Default::default)
+            .responses
+            .insert("Error".to_string(), RefOr::T(error));
+
+        for item in openapi.paths.paths.values_mut() {
+            for operation in operations_mut(item) {
+                operation
+                    .responses
+                    .responses
+                    .entry("default".to_string())
+                    .or_insert_with(|| RefOr::Ref(Ref::from_response_name("Error")));
+            }
+        }
+    }
+}
+
+/// Copy the operations of `SHARED_ROUTES` to the routes sharing them
+struct SharedRoutes;
+
+impl Modify for SharedRoutes {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        for (route, documented) in SHARED_ROUTES {
+            let Some(mut item) = openapi.paths.paths.get(*documented).cloned() else {
+                continue;
+            };
+            // Operation ids must be unique: the copy gets the route's own
+            for operation in operations_mut(&mut item) {
+                operation.operation_id = operation.operation_id.take().map(|id| format!("{}{}", id, operation_id_suffix(route)));
+            }
+            openapi.paths.paths.insert(route.to_string(), item);
+        }
+    }
+}
+
+/// `/api/public/inventory/search` -> `_api_public_inventory_search`
+fn operation_id_suffix(route: &str) -> String {
+    route.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect()
+}
+
+fn operations_mut(item: &mut PathItem) -> impl Iterator<Item = &mut Operation> {
+    [&mut item.get, &mut item.put, &mut item.post, &mut item.delete, &mut item.patch]
+        .into_iter()
+        .flatten()
+}
+
+fn operation_mut<'a>(item: &'a mut PathItem, method: &str) -> Option<&'a mut Operation> {
+    match method {
+        "GET" => item.get.as_mut(),
+        "PUT" => item.put.as_mut(),
+        "POST" => item.post.as_mut(),
+        "DELETE" => item.delete.as_mut(),
+        "PATCH" => item.patch.as_mut(),
+        _ => None,
+    }
+}
+
+/// Axum route template to OpenAPI path: `/api/x/:id` -> `/api/x/{id}`
+pub fn openapi_path(route: &str) -> String {
+    route
+        .split('/')
+        .map(|segment| match segment.strip_prefix(':') {
+            Some(name) => format!("{{{}}}", name),
+            None => segment.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// (method, path) of every documented operation, e.g. ("GET", "/api/licenses/{id}")
+pub fn documented_routes(openapi: &utoipa::openapi::OpenApi) -> Vec<(String, String)> {
+    let mut routes = Vec::new();
+    for (path, item) in openapi.paths.paths.iter() {
+        for (method, operation) in [
+            ("GET", &item.get),
+            ("PUT", &item.put),
+            ("POST", &item.post),
+            ("DELETE", &item.delete),
+            ("PATCH", &item.patch),
+        ] {
+            if operation.is_some() {
+                routes.push((method.to_string(), path.clone()));
+            }
+        }
+    }
+    routes
+}
+
+/// Mark the public routes of the route policy audit as needing no credentials,
+/// and note the role admin routes need
+pub fn document_route_policies(openapi: &mut utoipa::openapi::OpenApi, report: &RoutePolicyReport) {
+    for route in &report.routes {
+        let Some(operation) = openapi
+            .paths
+            .paths
+            .get_mut(&openapi_path(&route.path))
+            .and_then(|item| operation_mut(item, &route.method))
+        else {
+            continue;
+        };
+
+        let note = if let Some(reason) = route.public_reason {
+            operation.security = Some(Vec::new());
+            format!("Public: {}.", reason)
+        } else if route.has(RoutePolicy::Superadmin) {
+            "Requires the superadmin role.".to_string()
+        } else if route.has(RoutePolicy::Admin) {
+            "Requires the admin role.".to_string()
+        } else {
+            continue;
+        };
+
+        operation.description = Some(match operation.description.take() {
+            Some(description) if !description.is_empty() => format!("{}\n\n{}", description, note),
+            _ => note,
+        });
+    }
+}
+
+/// Built on first request, after main.rs has installed the route policy report
+static SPEC: Lazy<utoipa::openapi::OpenApi> = Lazy::new(|| {
+    let mut openapi = ApiDoc::openapi();
+    match route_policy_report() {
+        Some(report) => document_route_policies(&mut openapi, report),
+        None => tracing::warn!("No route policy report installed; the OpenAPI spec won't mark public routes"),
+    }
+    openapi
+});
+
+/// GET /api/openapi.json
+#[utoipa::path(
+    get,
+    path = "/api/openapi.json",
+    tag = "api_docs",
+    summary = "OpenAPI 3.1 description of this API",
+    responses(
+        (status = 200, description = "The OpenAPI document", body = serde_json::Value),
+    )
+)]
+pub async fn openapi_json() -> Json<&'static utoipa::openapi::OpenApi> {
+    Json(&*SPEC)
+}
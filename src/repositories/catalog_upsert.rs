@@ -0,0 +1,85 @@
+/// Shared pieces of the OpenFDA and EMA catalog batch upserts
+///
+/// Both catalogs upsert a whole chunk with one `INSERT ... SELECT FROM UNNEST(...)`
+/// statement: one bound array per column instead of one statement per entry. Array
+/// columns (routes, pharmacologic classes) travel as JSONB because `UNNEST` would
+/// flatten a `text[][]`.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::time::Duration;
+use crate::middleware::metrics;
+
+/// Entries per statement
+pub(crate) const UPSERT_CHUNK_SIZE: usize = 1_000;
+
+/// Chunks of at least `MIN_MEASURED_ROWS` upserting slower than this are logged.
+///
+/// Measured with 10,000 entries on a local database: the per-row statements this replaced
+/// ran at 1,100-2,300 rows/s; chunks run at 3,800-5,500 rows/s for OpenFDA (mostly spent
+/// in the catalog history trigger) and 9,500-12,000 rows/s for EMA.
+pub(crate) const TARGET_ROWS_PER_SECOND: f64 = 2_500.0;
+const MIN_MEASURED_ROWS: usize = 200;
+
+/// Keep the last entry of each key, in order, and count the dropped duplicates.
+///
+/// One statement can't update the same row twice, so duplicates within a chunk are
+/// collapsed up front. Each dropped duplicate counts as an update, as it did when
+/// every entry was its own statement.
+pub(crate) fn dedupe_last<T, K, F>(entries: &[T], key: F) -> (Vec<&T>, i32)
+where
+    K: Eq + Hash,
+    F: Fn(&T) -> K,
+{
+    let mut last: HashMap<K, usize> = HashMap::with_capacity(entries.len());
+    for (i, entry) in entries.iter().enumerate() {
+        last.insert(key(entry), i);
+    }
+
+    let kept: Vec<&T> = entries
+        .iter()
+        .enumerate()
+        .filter(|(i, entry)| last.get(&key(entry)) == Some(i))
+        .map(|(_, entry)| entry)
+        .collect();
+    let duplicates = (entries.len() - kept.len()) as i32;
+
+    (kept, duplicates)
+}
+
+/// Export the throughput of one upserted chunk and log it when below target
+pub(crate) fn record_throughput(source: &str, rows: usize, elapsed: Duration) {
+    let rows_per_second = rows as f64 / elapsed.as_secs_f64().max(f64::EPSILON);
+    metrics::record_catalog_upsert(source, rows, elapsed);
+
+    if rows >= MIN_MEASURED_ROWS && rows_per_second < TARGET_ROWS_PER_SECOND {
+        tracing::warn!(
+            "{} catalog upsert below target: {} rows in {}ms ({:.0} rows/s, target {:.0})",
+            source,
+            rows,
+            elapsed.as_millis(),
+            rows_per_second,
+            TARGET_ROWS_PER_SECOND
+        );
+    }
+}
+
+/// Text arrays as JSONB for transport through `UNNEST`; decoded in SQL with
+/// `ARRAY(SELECT jsonb_array_elements_text(...))`
+pub(crate) fn text_array_json(values: &Option<Vec<String>>) -> Option<serde_json::Value> {
+    values.as_ref().map(|values| serde_json::json!(values))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dedupe_keeps_last_occurrence() {
+        let entries = vec![("a", 1), ("b", 1), ("a", 2), ("c", 1), ("a", 3)];
+        let (kept, duplicates) = dedupe_last(&entries, |entry| entry.0);
+
+        assert_eq!(kept, vec![&("b", 1), &("c", 1), &("a", 3)]);
+        assert_eq!(duplicates, 2);
+    }
+}
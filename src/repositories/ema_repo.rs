@@ -1,11 +1,14 @@
-use sqlx::{PgPool, query, query_as, Row};
+use sqlx::{PgPool, Postgres, QueryBuilder, query, query_as, query_scalar, Row};
+use std::time::Instant;
 use uuid::Uuid;
 use chrono::Utc;
 use crate::models::ema::{
     EmaCatalogEntry, EmaSyncLog, EmaSearchRequest,
     EmaCatalogStats, LanguageCount, StatusCount, TherapeuticAreaCount
 };
+use crate::models::catalog_export::EmaExportParams;
 use crate::middleware::error_handling::{Result, AppError};
+use crate::repositories::catalog_upsert::{dedupe_last, record_throughput, text_array_json, UPSERT_CHUNK_SIZE};
 
 pub struct EmaRepository {
     pub pool: PgPool,
@@ -20,7 +23,8 @@ impl EmaRepository {
     // CRUD Operations
     // ============================================================================
 
-    /// Batch upsert multiple catalog entries efficiently
+    /// Batch upsert multiple catalog entries, one `UNNEST` statement per chunk.
+    /// Returns (inserted, updated).
     pub async fn batch_upsert(&self, entries: Vec<EmaCatalogEntry>) -> Result<(i32, i32)> {
         if entries.is_empty() {
             return Ok((0, 0));
@@ -29,10 +33,11 @@ impl EmaRepository {
         let mut inserted = 0;
         let mut updated = 0;
 
-        // Process in batches to avoid overwhelming the database
-        const BATCH_SIZE: usize = 50;
-        for batch in entries.chunks(BATCH_SIZE) {
+        for batch in entries.chunks(UPSERT_CHUNK_SIZE) {
+            let started = Instant::now();
             let (batch_inserted, batch_updated) = self.process_batch(batch).await?;
+            record_throughput("ema", batch.len(), started.elapsed());
+
             inserted += batch_inserted;
             updated += batch_updated;
         }
@@ -40,102 +45,126 @@ impl EmaRepository {
         Ok((inserted, updated))
     }
 
-    /// Process a single batch of entries
-    async fn process_batch(&self, entries: &[EmaCatalogEntry]) -> Result<(i32, i32)> {
-        let mut inserted = 0;
-        let mut updated = 0;
+    /// Upsert a single batch of entries with one statement
+    async fn process_batch(&self, batch: &[EmaCatalogEntry]) -> Result<(i32, i32)> {
+        let (entries, duplicates) = dedupe_last(batch, |entry| entry.eu_number.clone());
 
-        for entry in entries {
-            let result = query(
-                r#"
-                INSERT INTO ema_catalog (
-                    eu_number, pms_id, bundle_id, epi_id, product_name, inn_name,
-                    therapeutic_indication, mah_name, mah_country, authorization_status,
-                    authorization_date, authorization_country, procedure_type,
-                    pharmaceutical_form, route_of_administration, strength, active_substances,
-                    excipients, atc_code, atc_classification, therapeutic_area,
-                    orphan_designation, pharmacovigilance_status, additional_monitoring,
-                    risk_management_plan, language_code, epi_url, smpc_url, pil_url,
-                    epi_data, metadata, last_synced_at
-                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23, $24, $25, $26, $27, $28, $29, $30, $31, $32)
-                ON CONFLICT (eu_number) DO UPDATE SET
-                    pms_id = EXCLUDED.pms_id,
-                    bundle_id = EXCLUDED.bundle_id,
-                    epi_id = EXCLUDED.epi_id,
-                    product_name = EXCLUDED.product_name,
-                    inn_name = EXCLUDED.inn_name,
-                    therapeutic_indication = EXCLUDED.therapeutic_indication,
-                    mah_name = EXCLUDED.mah_name,
-                    mah_country = EXCLUDED.mah_country,
-                    authorization_status = EXCLUDED.authorization_status,
-                    authorization_date = EXCLUDED.authorization_date,
-                    authorization_country = EXCLUDED.authorization_country,
-                    procedure_type = EXCLUDED.procedure_type,
-                    pharmaceutical_form = EXCLUDED.pharmaceutical_form,
-                    route_of_administration = EXCLUDED.route_of_administration,
-                    strength = EXCLUDED.strength,
-                    active_substances = EXCLUDED.active_substances,
-                    excipients = EXCLUDED.excipients,
-                    atc_code = EXCLUDED.atc_code,
-                    atc_classification = EXCLUDED.atc_classification,
-                    therapeutic_area = EXCLUDED.therapeutic_area,
-                    orphan_designation = EXCLUDED.orphan_designation,
-                    pharmacovigilance_status = EXCLUDED.pharmacovigilance_status,
-                    additional_monitoring = EXCLUDED.additional_monitoring,
-                    risk_management_plan = EXCLUDED.risk_management_plan,
-                    language_code = EXCLUDED.language_code,
-                    epi_url = EXCLUDED.epi_url,
-                    smpc_url = EXCLUDED.smpc_url,
-                    pil_url = EXCLUDED.pil_url,
-                    epi_data = EXCLUDED.epi_data,
-                    metadata = EXCLUDED.metadata,
-                    last_synced_at = EXCLUDED.last_synced_at,
-                    updated_at = CURRENT_TIMESTAMP
-                RETURNING (xmax = 0) AS was_inserted
-                "#
+        let was_inserted: Vec<bool> = query_scalar(
+            r#"
+            INSERT INTO ema_catalog (
+                eu_number, pms_id, bundle_id, epi_id, product_name, inn_name,
+                therapeutic_indication, mah_name, mah_country, authorization_status,
+                authorization_date, authorization_country, procedure_type,
+                pharmaceutical_form, route_of_administration, strength, active_substances,
+                excipients, atc_code, atc_classification, therapeutic_area,
+                orphan_designation, pharmacovigilance_status, additional_monitoring,
+                risk_management_plan, language_code, epi_url, smpc_url, pil_url,
+                epi_data, metadata, last_synced_at
             )
-            .bind(&entry.eu_number)
-            .bind(&entry.pms_id)
-            .bind(&entry.bundle_id)
-            .bind(&entry.epi_id)
-            .bind(&entry.product_name)
-            .bind(&entry.inn_name)
-            .bind(&entry.therapeutic_indication)
-            .bind(&entry.mah_name)
-            .bind(&entry.mah_country)
-            .bind(&entry.authorization_status)
-            .bind(&entry.authorization_date)
-            .bind(&entry.authorization_country)
-            .bind(&entry.procedure_type)
-            .bind(&entry.pharmaceutical_form)
-            .bind(&entry.route_of_administration)
-            .bind(&entry.strength)
-            .bind(&entry.active_substances)
-            .bind(&entry.excipients)
-            .bind(&entry.atc_code)
-            .bind(&entry.atc_classification)
-            .bind(&entry.therapeutic_area)
-            .bind(&entry.orphan_designation)
-            .bind(&entry.pharmacovigilance_status)
-            .bind(&entry.additional_monitoring)
-            .bind(&entry.risk_management_plan)
-            .bind(&entry.language_code)
-            .bind(&entry.epi_url)
-            .bind(&entry.smpc_url)
-            .bind(&entry.pil_url)
-            .bind(&entry.epi_data)
-            .bind(&entry.metadata)
-            .bind(&entry.last_synced_at)
-            .fetch_one(&self.pool)
-            .await?;
+            SELECT
+                t.eu_number, t.pms_id, t.bundle_id, t.epi_id, t.product_name, t.inn_name,
+                t.therapeutic_indication, t.mah_name, t.mah_country, t.authorization_status,
+                t.authorization_date, t.authorization_country, t.procedure_type,
+                t.pharmaceutical_form,
+                CASE WHEN t.route_of_administration IS NULL THEN NULL
+                     ELSE ARRAY(SELECT jsonb_array_elements_text(t.route_of_administration)) END,
+                t.strength, t.active_substances,
+                t.excipients, t.atc_code, t.atc_classification, t.therapeutic_area,
+                t.orphan_designation, t.pharmacovigilance_status, t.additional_monitoring,
+                t.risk_management_plan, t.language_code, t.epi_url, t.smpc_url, t.pil_url,
+                t.epi_data, t.metadata, t.last_synced_at
+            FROM UNNEST(
+                $1::text[], $2::text[], $3::text[], $4::text[], $5::text[], $6::text[],
+                $7::text[], $8::text[], $9::text[], $10::text[],
+                $11::date[], $12::text[], $13::text[],
+                $14::text[], $15::jsonb[], $16::text[], $17::jsonb[],
+                $18::jsonb[], $19::text[], $20::text[], $21::text[],
+                $22::bool[], $23::text[], $24::bool[],
+                $25::bool[], $26::text[], $27::text[], $28::text[], $29::text[],
+                $30::jsonb[], $31::jsonb[], $32::timestamptz[]
+            ) AS t(
+                eu_number, pms_id, bundle_id, epi_id, product_name, inn_name,
+                therapeutic_indication, mah_name, mah_country, authorization_status,
+                authorization_date, authorization_country, procedure_type,
+                pharmaceutical_form, route_of_administration, strength, active_substances,
+                excipients, atc_code, atc_classification, therapeutic_area,
+                orphan_designation, pharmacovigilance_status, additional_monitoring,
+                risk_management_plan, language_code, epi_url, smpc_url, pil_url,
+                epi_data, metadata, last_synced_at
+            )
+            ON CONFLICT (eu_number) DO UPDATE SET
+                pms_id = EXCLUDED.pms_id,
+                bundle_id = EXCLUDED.bundle_id,
+                epi_id = EXCLUDED.epi_id,
+                product_name = EXCLUDED.product_name,
+                inn_name = EXCLUDED.inn_name,
+                therapeutic_indication = EXCLUDED.therapeutic_indication,
+                mah_name = EXCLUDED.mah_name,
+                mah_country = EXCLUDED.mah_country,
+                authorization_status = EXCLUDED.authorization_status,
+                authorization_date = EXCLUDED.authorization_date,
+                authorization_country = EXCLUDED.authorization_country,
+                procedure_type = EXCLUDED.procedure_type,
+                pharmaceutical_form = EXCLUDED.pharmaceutical_form,
+                route_of_administration = EXCLUDED.route_of_administration,
+                strength = EXCLUDED.strength,
+                active_substances = EXCLUDED.active_substances,
+                excipients = EXCLUDED.excipients,
+                atc_code = EXCLUDED.atc_code,
+                atc_classification = EXCLUDED.atc_classification,
+                therapeutic_area = EXCLUDED.therapeutic_area,
+                orphan_designation = EXCLUDED.orphan_designation,
+                pharmacovigilance_status = EXCLUDED.pharmacovigilance_status,
+                additional_monitoring = EXCLUDED.additional_monitoring,
+                risk_management_plan = EXCLUDED.risk_management_plan,
+                language_code = EXCLUDED.language_code,
+                epi_url = EXCLUDED.epi_url,
+                smpc_url = EXCLUDED.smpc_url,
+                pil_url = EXCLUDED.pil_url,
+                epi_data = EXCLUDED.epi_data,
+                metadata = EXCLUDED.metadata,
+                last_synced_at = EXCLUDED.last_synced_at,
+                updated_at = CURRENT_TIMESTAMP
+            RETURNING (xmax = 0) AS was_inserted
+            "#
+        )
+        .bind(entries.iter().map(|e| e.eu_number.clone()).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.pms_id.clone()).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.bundle_id.clone()).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.epi_id.clone()).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.product_name.clone()).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.inn_name.clone()).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.therapeutic_indication.clone()).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.mah_name.clone()).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.mah_country.clone()).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.authorization_status.clone()).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.authorization_date).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.authorization_country.clone()).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.procedure_type.clone()).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.pharmaceutical_form.clone()).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| text_array_json(&e.route_of_administration)).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.strength.clone()).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.active_substances.clone()).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.excipients.clone()).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.atc_code.clone()).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.atc_classification.clone()).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.therapeutic_area.clone()).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.orphan_designation).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.pharmacovigilance_status.clone()).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.additional_monitoring).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.risk_management_plan).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.language_code.clone()).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.epi_url.clone()).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.smpc_url.clone()).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.pil_url.clone()).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.epi_data.clone()).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.metadata.clone()).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.last_synced_at).collect::<Vec<_>>())
+        .fetch_all(&self.pool)
+        .await?;
 
-            let was_inserted: bool = result.try_get("was_inserted")?;
-            if was_inserted {
-                inserted += 1;
-            } else {
-                updated += 1;
-            }
-        }
+        let inserted = was_inserted.iter().filter(|inserted| **inserted).count() as i32;
+        let updated = was_inserted.len() as i32 - inserted + duplicates;
 
         Ok((inserted, updated))
     }
@@ -430,6 +459,46 @@ impl EmaRepository {
         Ok(entry)
     }
 
+    /// Find every stored entry among the given EU numbers
+    pub async fn find_by_eu_numbers(&self, eu_numbers: &[String]) -> Result<Vec<EmaCatalogEntry>> {
+        let entries = query_as::<_, EmaCatalogEntry>(
+            "SELECT * FROM ema_catalog WHERE eu_number = ANY($1)"
+        )
+        .bind(eu_numbers)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(entries)
+    }
+
+    /// Number of entries an export with these filters covers
+    pub async fn count_export(&self, params: &EmaExportParams) -> Result<i64> {
+        let mut builder = QueryBuilder::<Postgres>::new("SELECT COUNT(*) FROM ema_catalog");
+        push_export_filters(&mut builder, params, params.after.as_deref());
+
+        let count: i64 = builder.build_query_scalar::<i64>().fetch_one(&self.pool).await?;
+        Ok(count)
+    }
+
+    /// A page of an export in EU number order, starting after `after` and skipping `offset` rows
+    pub async fn export_page(
+        &self,
+        params: &EmaExportParams,
+        after: Option<&str>,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<EmaCatalogEntry>> {
+        let mut builder = QueryBuilder::<Postgres>::new("SELECT * FROM ema_catalog");
+        push_export_filters(&mut builder, params, after);
+        builder.push(" ORDER BY eu_number LIMIT ");
+        builder.push_bind(limit);
+        builder.push(" OFFSET ");
+        builder.push_bind(offset);
+
+        let entries = builder.build_query_as::<EmaCatalogEntry>().fetch_all(&self.pool).await?;
+        Ok(entries)
+    }
+
     /// Get total count of catalog entries
     pub async fn get_total_count(&self) -> Result<i64> {
         let row = query("SELECT COUNT(*) as count FROM ema_catalog")
@@ -653,8 +722,8 @@ impl EmaRepository {
         let row = query(
             r#"
             INSERT INTO ema_sync_log (
-                sync_started_at, language_code, sync_type, record_limit, status
-            ) VALUES ($1, $2, $3, $4, 'in_progress')
+                sync_started_at, language_code, sync_type, record_limit, status, last_heartbeat_at
+            ) VALUES ($1, $2, $3, $4, 'in_progress', $1)
             RETURNING id
             "#
         )
@@ -692,8 +761,9 @@ impl EmaRepository {
                 records_failed = $6,
                 api_response_time_ms = $7,
                 processing_time_ms = $8,
+                records_processed = $2,
                 status = 'completed'
-            WHERE id = $9
+            WHERE id = $9 AND status = 'in_progress'
             "#
         )
         .bind(Utc::now())
@@ -711,6 +781,41 @@ impl EmaRepository {
         Ok(())
     }
 
+    /// Mark a completed run as suspect. Returns false if the run is not (or no longer) completed.
+    pub async fn mark_sync_suspect(&self, log_id: Uuid, anomalies: &serde_json::Value) -> Result<bool> {
+        let result = query(
+            "UPDATE ema_sync_log SET status = 'suspect', anomalies = $2 WHERE id = $1 AND status = 'completed'"
+        )
+        .bind(log_id)
+        .bind(anomalies)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Move a sync back to in_progress for another attempt of its background job.
+    /// Returns false if the sync was cancelled or has finished.
+    pub async fn restart_sync_log(&self, log_id: Uuid) -> Result<bool> {
+        let result = query(
+            r#"
+            UPDATE ema_sync_log
+            SET status = 'in_progress',
+                error_message = NULL,
+                sync_completed_at = NULL,
+                last_heartbeat_at = NOW()
+            WHERE id = $1
+              AND cancelled_at IS NULL
+              AND status IN ('in_progress', 'failed', 'interrupted')
+            "#
+        )
+        .bind(log_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
     /// Mark sync log as failed
     pub async fn fail_sync_log(
         &self,
@@ -725,7 +830,7 @@ impl EmaRepository {
                 status = 'failed',
                 error_message = $2,
                 warning_messages = $3
-            WHERE id = $4
+            WHERE id = $4 AND status = 'in_progress'
             "#
         )
         .bind(Utc::now())
@@ -738,6 +843,141 @@ impl EmaRepository {
         Ok(())
     }
 
+    /// Record the expected size of a running sync
+    pub async fn set_total_expected(&self, log_id: Uuid, total_expected: i32, total_batches: i32) -> Result<()> {
+        query(
+            r#"
+            UPDATE ema_sync_log
+            SET total_expected = $2, total_batches = $3, last_heartbeat_at = NOW()
+            WHERE id = $1
+            "#
+        )
+        .bind(log_id)
+        .bind(total_expected)
+        .bind(total_batches)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Update running totals after a batch (also refreshes the heartbeat)
+    #[allow(clippy::too_many_arguments)]
+    pub async fn update_sync_progress(
+        &self,
+        log_id: Uuid,
+        records_fetched: i32,
+        records_inserted: i32,
+        records_updated: i32,
+        records_skipped: i32,
+        records_failed: i32,
+        current_batch: i32,
+    ) -> Result<()> {
+        query(
+            r#"
+            UPDATE ema_sync_log
+            SET records_fetched = $2,
+                records_inserted = $3,
+                records_updated = $4,
+                records_skipped = $5,
+                records_failed = $6,
+                records_processed = $2,
+                current_batch = $7,
+                last_heartbeat_at = NOW()
+            WHERE id = $1 AND status = 'in_progress'
+            "#
+        )
+        .bind(log_id)
+        .bind(records_fetched)
+        .bind(records_inserted)
+        .bind(records_updated)
+        .bind(records_skipped)
+        .bind(records_failed)
+        .bind(current_batch)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Cancel a running sync; the worker stops at its next batch
+    pub async fn cancel_sync(&self, log_id: Uuid, cancelled_by: Uuid) -> Result<bool> {
+        let result = query(
+            r#"
+            UPDATE ema_sync_log
+            SET status = 'cancelled',
+                cancelled_at = $1,
+                cancelled_by = $2,
+                sync_completed_at = $1
+            WHERE id = $3 AND status = 'in_progress'
+            "#
+        )
+        .bind(Utc::now())
+        .bind(cancelled_by)
+        .bind(log_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Whether a sync has been cancelled
+    pub async fn is_sync_cancelled(&self, log_id: Uuid) -> Result<bool> {
+        let row = query("SELECT status = 'cancelled' AS cancelled FROM ema_sync_log WHERE id = $1")
+            .bind(log_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        match row {
+            Some(row) => Ok(row.try_get("cancelled")?),
+            None => Ok(false),
+        }
+    }
+
+    /// Check if there's an active sync running (interrupted syncs without a recent heartbeat don't count)
+    pub async fn is_sync_running(&self) -> Result<bool> {
+        let row = query(
+            r#"
+            SELECT EXISTS(
+                SELECT 1 FROM ema_sync_log
+                WHERE status = 'in_progress'
+                  AND COALESCE(last_heartbeat_at, sync_started_at) >= NOW() - INTERVAL '1 minute' * $1
+            ) as running
+            "#
+        )
+        .bind(EmaSyncLog::STALE_AFTER_MINUTES as i32)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let running: bool = row.try_get("running")?;
+        Ok(running)
+    }
+
+    /// Most recent in-progress sync
+    pub async fn get_active_sync(&self) -> Result<Option<EmaSyncLog>> {
+        let log = query_as::<_, EmaSyncLog>(
+            r#"
+            SELECT * FROM ema_sync_log
+            WHERE status = 'in_progress'
+            ORDER BY sync_started_at DESC
+            LIMIT 1
+            "#
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(log)
+    }
+
+    pub async fn get_sync_log(&self, log_id: Uuid) -> Result<Option<EmaSyncLog>> {
+        let log = query_as::<_, EmaSyncLog>("SELECT * FROM ema_sync_log WHERE id = $1")
+            .bind(log_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(log)
+    }
+
     /// Get the last successful sync
     pub async fn get_last_successful_sync(&self) -> Result<Option<EmaSyncLog>> {
         let log = query_as::<_, EmaSyncLog>(
@@ -881,4 +1121,39 @@ impl EmaRepository {
         let deleted_count = result.rows_affected();
         Ok(deleted_count as i64)
     }
-}
\ No newline at end of file
+}
+fn push_export_filters(builder: &mut QueryBuilder<Postgres>, params: &EmaExportParams, after: Option<&str>) {
+    builder.push(" WHERE TRUE");
+    if let Some(language) = &params.language {
+        builder.push(" AND language_code = ");
+        builder.push_bind(language.clone());
+    }
+    if let Some(status) = &params.authorization_status {
+        builder.push(" AND authorization_status ILIKE ");
+        builder.push_bind(status.clone());
+    }
+    if let Some(area) = params.therapeutic_area.as_deref().filter(|a| !a.trim().is_empty()) {
+        builder.push(" AND therapeutic_area ILIKE ");
+        builder.push_bind(format!("%{}%", area.trim()));
+    }
+    if let Some(atc_code) = params.atc_code.as_deref().filter(|c| !c.trim().is_empty()) {
+        builder.push(" AND atc_code ILIKE ");
+        builder.push_bind(format!("{}%", atc_code.trim()));
+    }
+    if let Some(mah_name) = params.mah_name.as_deref().filter(|m| !m.trim().is_empty()) {
+        builder.push(" AND mah_name ILIKE ");
+        builder.push_bind(format!("%{}%", mah_name.trim()));
+    }
+    if let Some(orphan) = params.orphan_designation {
+        builder.push(" AND COALESCE(orphan_designation, FALSE) = ");
+        builder.push_bind(orphan);
+    }
+    if let Some(updated_since) = params.updated_since {
+        builder.push(" AND updated_at >= ");
+        builder.push_bind(updated_since);
+    }
+    if let Some(after) = after {
+        builder.push(" AND eu_number > ");
+        builder.push_bind(after.to_string());
+    }
+}
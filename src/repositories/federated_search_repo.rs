@@ -0,0 +1,141 @@
+use sqlx::{PgPool, query_as};
+use crate::models::federated_search::FederatedSearchRow;
+use crate::middleware::error_handling::Result;
+
+pub struct FederatedSearchRepository {
+    pool: PgPool,
+}
+
+impl FederatedSearchRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Search every requested catalog in one query. Results are ordered by relevance, then
+    /// product name, source and source id, which is a total order, so pages never overlap
+    /// or skip rows regardless of how many sources contribute to them.
+    pub async fn search(
+        &self,
+        sources: &[String],
+        query_text: Option<&str>,
+        ingredient: Option<&str>,
+        atc_prefix: Option<&str>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<FederatedSearchRow>> {
+        let rows = query_as::<_, FederatedSearchRow>(
+            r#"
+            WITH matches AS (
+                SELECT
+                    'openfda'::TEXT AS source,
+                    o.product_ndc::TEXT AS source_id,
+                    'US'::TEXT AS country,
+                    o.brand_name AS product_name,
+                    CASE WHEN jsonb_typeof(o.active_ingredients) = 'array'
+                         THEN ARRAY(SELECT i->>'name' FROM jsonb_array_elements(o.active_ingredients) i
+                                    WHERE i->>'name' IS NOT NULL)
+                         ELSE ARRAY[o.generic_name] END AS active_ingredients,
+                    o.strength,
+                    o.dosage_form,
+                    o.labeler_name AS manufacturer,
+                    o.marketing_category AS authorization_status,
+                    (SELECT MIN(m.atc_code) FROM rxnorm_atc_mapping m
+                     WHERE o.openfda_data->'rxcui' @> to_jsonb(m.rxcui)) AS atc_code,
+                    CASE WHEN jsonb_typeof(o.openfda_data->'rxcui') = 'array'
+                         THEN ARRAY(SELECT jsonb_array_elements_text(o.openfda_data->'rxcui'))
+                         ELSE ARRAY[]::TEXT[] END AS rxcuis
+                FROM openfda_catalog o
+                WHERE 'openfda' = ANY($1)
+                  AND ($2::TEXT IS NULL
+                       OR o.search_vector @@ plainto_tsquery('english', $2)
+                       OR o.brand_name ILIKE '%' || $2 || '%'
+                       OR o.generic_name ILIKE '%' || $2 || '%'
+                       OR o.product_ndc = $2)
+                  AND ($3::TEXT IS NULL OR o.generic_name ILIKE '%' || $3 || '%')
+                  AND ($4::TEXT IS NULL OR EXISTS (
+                        SELECT 1 FROM rxnorm_atc_mapping m
+                        WHERE m.atc_code LIKE $4 || '%'
+                          AND o.openfda_data->'rxcui' @> to_jsonb(m.rxcui)))
+
+                UNION ALL
+
+                SELECT
+                    'ema'::TEXT,
+                    e.eu_number::TEXT,
+                    'EU'::TEXT,
+                    e.product_name,
+                    CASE WHEN jsonb_typeof(e.active_substances) = 'array'
+                              AND jsonb_array_length(e.active_substances) > 0
+                         THEN ARRAY(SELECT i->>'name' FROM jsonb_array_elements(e.active_substances) i
+                                    WHERE i->>'name' IS NOT NULL)
+                         ELSE ARRAY_REMOVE(ARRAY[e.inn_name], NULL) END,
+                    e.strength,
+                    e.pharmaceutical_form,
+                    e.mah_name,
+                    e.authorization_status,
+                    e.atc_code,
+                    ARRAY[]::TEXT[]
+                FROM ema_catalog e
+                WHERE 'ema' = ANY($1)
+                  AND ($2::TEXT IS NULL
+                       OR e.search_vector @@ plainto_tsquery('english', $2)
+                       OR e.product_name ILIKE '%' || $2 || '%'
+                       OR e.inn_name ILIKE '%' || $2 || '%'
+                       OR e.eu_number = $2)
+                  AND ($3::TEXT IS NULL
+                       OR e.inn_name ILIKE '%' || $3 || '%'
+                       OR e.active_substances::TEXT ILIKE '%' || $3 || '%')
+                  AND ($4::TEXT IS NULL OR UPPER(e.atc_code) LIKE $4 || '%')
+
+                UNION ALL
+
+                SELECT
+                    r.source,
+                    r.source_id,
+                    r.country,
+                    r.product_name,
+                    r.active_ingredients,
+                    r.strength,
+                    r.dosage_form,
+                    r.authorization_holder,
+                    r.authorization_status,
+                    r.atc_code,
+                    ARRAY[]::TEXT[]
+                FROM regulator_catalog r
+                WHERE r.source = ANY($1)
+                  AND ($2::TEXT IS NULL
+                       OR r.search_vector @@ plainto_tsquery('simple', $2)
+                       OR r.product_name ILIKE '%' || $2 || '%'
+                       OR r.source_id = $2)
+                  AND ($3::TEXT IS NULL OR EXISTS (
+                        SELECT 1 FROM unnest(r.active_ingredients) AS ingredient
+                        WHERE ingredient ILIKE '%' || $3 || '%'))
+                  AND ($4::TEXT IS NULL OR UPPER(r.atc_code) LIKE $4 || '%')
+            )
+            SELECT source, source_id, country, product_name, active_ingredients, strength,
+                   dosage_form, manufacturer, authorization_status, atc_code, rxcuis
+            FROM matches
+            ORDER BY
+                CASE WHEN $2::TEXT IS NULL THEN 0
+                     WHEN LOWER(product_name) = LOWER($2) OR source_id = $2 THEN 3
+                     WHEN product_name ILIKE $2 || '%' THEN 2
+                     WHEN product_name ILIKE '%' || $2 || '%' THEN 1
+                     ELSE 0 END DESC,
+                LOWER(product_name),
+                source,
+                source_id
+            LIMIT $5 OFFSET $6
+            "#
+        )
+        .bind(sources)
+        .bind(query_text)
+        .bind(ingredient)
+        .bind(atc_prefix)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+}
@@ -1,7 +1,8 @@
-use sqlx::{PgPool, query, Row};
+use sqlx::{PgPool, postgres::PgRow, query, query_as, Row};
 use uuid::Uuid;
 use chrono::Utc;
 use crate::models::inventory::{Inventory, InventoryWithDetails, CreateInventoryRequest, UpdateInventoryRequest, SearchInventoryRequest};
+use crate::models::ingredient::Allergen;
 use crate::middleware::error_handling::{Result, AppError};
 
 pub struct InventoryRepository {
@@ -13,6 +14,11 @@ impl InventoryRepository {
         Self { pool }
     }
 
+    /// Get reference to the database pool (for direct queries)
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+
     pub async fn create(&self, request: &CreateInventoryRequest, user_id: Uuid) -> Result<Inventory> {
         let row = query(
             r#"
@@ -77,6 +83,18 @@ impl InventoryRepository {
         }
     }
 
+    /// Inventory records among the given ids; missing ids are skipped. Order is unspecified.
+    pub async fn find_by_ids(&self, ids: &[Uuid]) -> Result<Vec<Inventory>> {
+        let inventories = query_as::<_, Inventory>(
+            "SELECT id, user_id, pharmaceutical_id, batch_number, quantity, expiry_date, unit_price, storage_location, status, created_at, updated_at FROM inventory WHERE id = ANY($1)"
+        )
+        .bind(ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(inventories)
+    }
+
     pub async fn find_by_user(&self, user_id: Uuid, limit: Option<i64>, offset: Option<i64>) -> Result<Vec<Inventory>> {
         let limit = limit.unwrap_or(50).min(100);
         let offset = offset.unwrap_or(0);
@@ -112,27 +130,45 @@ impl InventoryRepository {
         Ok(inventories)
     }
 
+    /// Whether the listing is visible on the marketplace: not taken down and its owner
+    /// neither suspended nor without listing rights
+    pub async fn is_listed(&self, id: Uuid) -> Result<bool> {
+        let listed: bool = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM inventory i JOIN users u ON u.id = i.user_id
+                WHERE i.id = $1 AND i.taken_down_at IS NULL
+                  AND u.suspended_at IS NULL AND u.listing_suspended_at IS NULL
+            )
+            "#
+        )
+        .bind(id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(listed)
+    }
+
     pub async fn search_with_details(&self, request: &SearchInventoryRequest) -> Result<Vec<InventoryWithDetails>> {
         let limit = request.limit.unwrap_or(50).min(100);
         let offset = request.offset.unwrap_or(0);
 
         // Use a simpler, production-ready approach with a well-structured query
-        let mut query_str = r#"
-            SELECT
-                i.id, i.user_id, i.pharmaceutical_id, i.batch_number, i.quantity, i.expiry_date,
-                i.unit_price, i.storage_location, i.status, i.created_at, i.updated_at,
-                u.id as u_id, u.email, u.company_name, u.contact_person, u.phone, u.address, u.license_number, u.is_verified, u.role, u.created_at as user_created_at,
-                p.id as pharma_id, p.brand_name, p.generic_name, p.ndc_code, p.manufacturer, p.category, p.description, p.strength, p.dosage_form, p.storage_requirements, p.created_at as pharma_created_at
-            FROM inventory i
-            JOIN pharmaceuticals p ON i.pharmaceutical_id = p.id
-            JOIN users u ON i.user_id = u.id
-            WHERE i.status = 'available'
-        "#.to_string();
+        let mut query_str = LISTED_WITH_DETAILS.to_string();
 
         let mut params = Vec::new();
         let mut param_count = 0;
 
         // Add filters safely with parameter binding
+        if let Some(q) = request.q.as_deref().map(str::trim).filter(|q| !q.is_empty()) {
+            query_str.push_str(&format!(
+                " AND (p.brand_name ILIKE ${0} OR p.generic_name ILIKE ${0} OR p.manufacturer ILIKE ${0} OR u.company_name ILIKE ${0})",
+                param_count + 1
+            ));
+            params.push(format!("%{}%", q));
+            param_count += 1;
+        }
+
         if let Some(pharma_id) = request.pharmaceutical_id {
             query_str.push_str(&format!(" AND i.pharmaceutical_id = ${}", param_count + 1));
             params.push(pharma_id.to_string());
@@ -205,6 +241,26 @@ impl InventoryRepository {
             param_count += 1;
         }
 
+        if let Some(product_domain) = request.product_domain {
+            query_str.push_str(&format!(" AND p.product_domain = ${}", param_count + 1));
+            params.push(product_domain.as_str().to_string());
+            param_count += 1;
+        }
+
+        if let Some(ref free_of) = request.free_of {
+            let allergens = Allergen::parse_filter(free_of).map_err(AppError::BadRequest)?;
+            if !allergens.is_empty() {
+                // Only products whose excipients have been declared can be "free of" anything
+                query_str.push_str(&format!(
+                    " AND p.ingredients_declared_at IS NOT NULL AND NOT EXISTS (SELECT 1 FROM pharmaceutical_ingredients pi WHERE pi.pharmaceutical_id = p.id AND pi.allergens && ${}::text[])",
+                    param_count + 1
+                ));
+                let keys: Vec<&str> = allergens.iter().map(|a| a.as_str()).collect();
+                params.push(format!("{{{}}}", keys.join(",")));
+                param_count += 1;
+            }
+        }
+
         // Add ordering and pagination
         let sort_by = request.sort_by.as_deref().unwrap_or("expiry_date");
         let sort_order = request.sort_order.as_deref().unwrap_or("asc");
@@ -218,97 +274,18 @@ impl InventoryRepository {
 
         let rows = query_builder.fetch_all(&self.pool).await?;
 
-        // Process results with explicit error handling
-        let mut results = Vec::new();
-        for row in rows {
-            // Extract inventory data with proper error handling
-            let inventory = Inventory {
-                id: row.try_get("id")
-                    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get inventory id: {}", e)))?,
-                user_id: row.try_get("user_id")
-                    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get user_id: {}", e)))?,
-                pharmaceutical_id: row.try_get("pharmaceutical_id")
-                    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get pharmaceutical_id: {}", e)))?,
-                batch_number: row.try_get("batch_number")
-                    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get batch_number: {}", e)))?,
-                quantity: row.try_get("quantity")
-                    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get quantity: {}", e)))?,
-                expiry_date: row.try_get("expiry_date")
-                    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get expiry_date: {}", e)))?,
-                unit_price: row.try_get("unit_price")
-                    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get unit_price: {}", e)))?,
-                storage_location: row.try_get("storage_location")
-                    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get storage_location: {}", e)))?,
-                status: row.try_get("status")
-                    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get status: {}", e)))?,
-                created_at: row.try_get("created_at")
-                    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get created_at: {}", e)))?,
-                updated_at: row.try_get("updated_at")
-                    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get updated_at: {}", e)))?,
-            };
-
-            // Extract user data
-            let user = crate::models::user::UserResponse {
-                id: row.try_get("u_id")
-                    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get user id: {}", e)))?,
-                email: row.try_get("email")
-                    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get email: {}", e)))?,
-                company_name: row.try_get("company_name")
-                    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get company_name: {}", e)))?,
-                contact_person: row.try_get("contact_person")
-                    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get contact_person: {}", e)))?,
-                phone: row.try_get("phone")
-                    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get phone: {}", e)))?,
-                address: row.try_get("address")
-                    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get address: {}", e)))?,
-                license_number: row.try_get("license_number")
-                    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get license_number: {}", e)))?,
-                is_verified: row.try_get("is_verified")
-                    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get is_verified: {}", e)))?,
-                role: row.try_get("role")
-                    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get role: {}", e)))?,
-                created_at: row.try_get("user_created_at")
-                    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get user_created_at: {}", e)))?,
-            };
-
-            // Extract pharmaceutical data
-            let pharmaceutical = crate::models::pharmaceutical::PharmaceuticalResponse {
-                id: row.try_get("pharma_id")
-                    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get pharma_id: {}", e)))?,
-                brand_name: row.try_get("brand_name")
-                    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get brand_name: {}", e)))?,
-                generic_name: row.try_get("generic_name")
-                    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get generic_name: {}", e)))?,
-                ndc_code: row.try_get("ndc_code")
-                    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get ndc_code: {}", e)))?,
-                manufacturer: row.try_get("manufacturer")
-                    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get manufacturer: {}", e)))?,
-                category: row.try_get("category")
-                    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get category: {}", e)))?,
-                description: row.try_get("description")
-                    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get description: {}", e)))?,
-                strength: row.try_get("strength")
-                    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get strength: {}", e)))?,
-                dosage_form: row.try_get("dosage_form")
-                    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get dosage_form: {}", e)))?,
-                storage_requirements: row.try_get("storage_requirements")
-                    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get storage_requirements: {}", e)))?,
-                created_at: row.try_get("pharma_created_at")
-                    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get pharma_created_at: {}", e)))?,
-            };
-
-            // Calculate days to expiry
-            let days_to_expiry = inventory.expiry_date.signed_duration_since(chrono::Utc::now().date_naive()).num_days();
-
-            // Build the response
-            results.push(InventoryWithDetails {
-                inventory: inventory.clone(),
-                pharmaceutical: pharmaceutical.clone(),
-                user: user.clone(),
-            });
-        }
+        rows.iter().map(details_from_row).collect()
+    }
+
+    /// Marketplace listings by id, with product and seller; ids of listings not on the
+    /// marketplace are skipped. Order is unspecified.
+    pub async fn find_listed_with_details(&self, ids: &[Uuid]) -> Result<Vec<InventoryWithDetails>> {
+        let rows = query(&format!("{} AND i.id = ANY($1)", LISTED_WITH_DETAILS))
+            .bind(ids)
+            .fetch_all(&self.pool)
+            .await?;
 
-        Ok(results)
+        rows.iter().map(details_from_row).collect()
     }
 
     pub async fn update(&self, inventory_id: Uuid, user_id: Uuid, request: &UpdateInventoryRequest) -> Result<Inventory> {
@@ -423,6 +400,7 @@ impl InventoryRepository {
 
         // Use the same search_with_details logic but with expiry filtering
         let expiry_request = SearchInventoryRequest {
+            q: None,
             pharmaceutical_id: None,
             brand_name: None,
             generic_name: None,
@@ -435,6 +413,9 @@ impl InventoryRepository {
             status: Some("available".to_string()),
             min_price: None,
             max_price: None,
+            product_domain: None,
+            free_of: None,
+            facets: None,
             limit: Some(1000), // High limit for alerts
             offset: Some(0),
             sort_by: Some("expiry_date".to_string()),
@@ -465,4 +446,111 @@ impl InventoryRepository {
 
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+/// Marketplace listings with their product and seller, for `details_from_row`
+const LISTED_WITH_DETAILS: &str = r#"
+    SELECT
+        i.id, i.user_id, i.pharmaceutical_id, i.batch_number, i.quantity, i.expiry_date,
+        i.unit_price, i.storage_location, i.status, i.created_at, i.updated_at,
+        u.id as u_id, u.email, u.company_name, u.contact_person, u.phone, u.address, u.license_number, u.is_verified, u.role, u.created_at as user_created_at,
+        p.id as pharma_id, p.brand_name, p.generic_name, p.ndc_code, p.manufacturer, p.category, p.description, p.strength, p.dosage_form, p.storage_requirements, p.product_domain, p.udi_di, p.device_class, p.target_species, p.created_at as pharma_created_at
+    FROM inventory i
+    JOIN pharmaceuticals p ON i.pharmaceutical_id = p.id
+    JOIN users u ON i.user_id = u.id
+    WHERE i.status = 'available' AND i.taken_down_at IS NULL
+      AND u.listing_suspended_at IS NULL AND u.suspended_at IS NULL
+"#;
+
+fn details_from_row(row: &PgRow) -> Result<InventoryWithDetails> {
+        // Extract inventory data with proper error handling
+        let inventory = Inventory {
+            id: row.try_get("id")
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get inventory id: {}", e)))?,
+            user_id: row.try_get("user_id")
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get user_id: {}", e)))?,
+            pharmaceutical_id: row.try_get("pharmaceutical_id")
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get pharmaceutical_id: {}", e)))?,
+            batch_number: row.try_get("batch_number")
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get batch_number: {}", e)))?,
+            quantity: row.try_get("quantity")
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get quantity: {}", e)))?,
+            expiry_date: row.try_get("expiry_date")
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get expiry_date: {}", e)))?,
+            unit_price: row.try_get("unit_price")
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get unit_price: {}", e)))?,
+            storage_location: row.try_get("storage_location")
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get storage_location: {}", e)))?,
+            status: row.try_get("status")
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get status: {}", e)))?,
+            created_at: row.try_get("created_at")
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get created_at: {}", e)))?,
+            updated_at: row.try_get("updated_at")
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get updated_at: {}", e)))?,
+        };
+
+        // Extract user data
+        let user = crate::models::user::UserResponse {
+            id: row.try_get("u_id")
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get user id: {}", e)))?,
+            email: row.try_get("email")
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get email: {}", e)))?,
+            company_name: row.try_get("company_name")
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get company_name: {}", e)))?,
+            contact_person: row.try_get("contact_person")
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get contact_person: {}", e)))?,
+            phone: row.try_get("phone")
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get phone: {}", e)))?,
+            address: row.try_get("address")
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get address: {}", e)))?,
+            license_number: row.try_get("license_number")
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get license_number: {}", e)))?,
+            is_verified: row.try_get("is_verified")
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get is_verified: {}", e)))?,
+            role: row.try_get("role")
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get role: {}", e)))?,
+            created_at: row.try_get("user_created_at")
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get user_created_at: {}", e)))?,
+        };
+
+        // Extract pharmaceutical data
+        let pharmaceutical = crate::models::pharmaceutical::PharmaceuticalResponse {
+            id: row.try_get("pharma_id")
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get pharma_id: {}", e)))?,
+            brand_name: row.try_get("brand_name")
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get brand_name: {}", e)))?,
+            generic_name: row.try_get("generic_name")
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get generic_name: {}", e)))?,
+            ndc_code: row.try_get("ndc_code")
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get ndc_code: {}", e)))?,
+            manufacturer: row.try_get("manufacturer")
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get manufacturer: {}", e)))?,
+            category: row.try_get("category")
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get category: {}", e)))?,
+            description: row.try_get("description")
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get description: {}", e)))?,
+            strength: row.try_get("strength")
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get strength: {}", e)))?,
+            dosage_form: row.try_get("dosage_form")
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get dosage_form: {}", e)))?,
+            storage_requirements: row.try_get("storage_requirements")
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get storage_requirements: {}", e)))?,
+            product_domain: row.try_get("product_domain")
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get product_domain: {}", e)))?,
+            udi_di: row.try_get("udi_di")
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get udi_di: {}", e)))?,
+            device_class: row.try_get("device_class")
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get device_class: {}", e)))?,
+            target_species: row.try_get("target_species")
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get target_species: {}", e)))?,
+            created_at: row.try_get("pharma_created_at")
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get pharma_created_at: {}", e)))?,
+            catalog_entry: None,
+        };
+
+    Ok(InventoryWithDetails {
+        inventory,
+        pharmaceutical,
+        user,
+    })
+}
@@ -1,4 +1,4 @@
-use sqlx::{PgPool, query, Row};
+use sqlx::{PgPool, query, query_as, Row};
 use uuid::Uuid;
 use crate::models::marketplace::{Inquiry, CreateInquiryRequest, UpdateInquiryRequest, Transaction, CreateTransactionRequest};
 use crate::middleware::error_handling::{Result, AppError};
@@ -82,6 +82,18 @@ impl MarketplaceRepository {
         }
     }
 
+    /// Inquiries among the given ids; missing ids are skipped. Order is unspecified.
+    pub async fn find_inquiries_by_ids(&self, ids: &[Uuid]) -> Result<Vec<Inquiry>> {
+        let inquiries = query_as::<_, Inquiry>(
+            "SELECT id, inventory_id, buyer_id, quantity_requested, message, status, created_at, updated_at FROM inquiries WHERE id = ANY($1)"
+        )
+        .bind(ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(inquiries)
+    }
+
     pub async fn get_inquiries_for_buyer(&self, buyer_id: Uuid, limit: Option<i64>, offset: Option<i64>) -> Result<Vec<Inquiry>> {
         let limit = limit.unwrap_or(50).min(100);
         let offset = offset.unwrap_or(0);
@@ -191,7 +203,8 @@ impl MarketplaceRepository {
             r#"
             INSERT INTO transactions (inquiry_id, seller_id, buyer_id, quantity, unit_price, total_price, status)
             VALUES ($1, $2, $3, $4, $5, $6, 'pending')
-            RETURNING id, inquiry_id, seller_id, buyer_id, quantity, unit_price, total_price, transaction_date, status
+            RETURNING id, inquiry_id, seller_id, buyer_id, quantity, unit_price, total_price, transaction_date, status,
+                      erp_sales_order_number, erp_purchase_order_number
             "#
         )
         .bind(&request.inquiry_id)
@@ -213,12 +226,16 @@ impl MarketplaceRepository {
             total_price: row.try_get("total_price")?,
             transaction_date: row.try_get("transaction_date")?,
             status: row.try_get("status")?,
+            erp_sales_order_number: row.try_get("erp_sales_order_number")?,
+            erp_purchase_order_number: row.try_get("erp_purchase_order_number")?,
         })
     }
 
     pub async fn find_transaction_by_id(&self, id: Uuid) -> Result<Option<Transaction>> {
         let row = query(
-            "SELECT id, inquiry_id, seller_id, buyer_id, quantity, unit_price, total_price, transaction_date, status FROM transactions WHERE id = $1"
+            "SELECT id, inquiry_id, seller_id, buyer_id, quantity, unit_price, total_price, transaction_date, status,
+                    erp_sales_order_number, erp_purchase_order_number
+             FROM transactions WHERE id = $1"
         )
         .bind(id)
         .fetch_optional(&self.pool)
@@ -235,6 +252,8 @@ impl MarketplaceRepository {
                 total_price: row.try_get("total_price")?,
                 transaction_date: row.try_get("transaction_date")?,
                 status: row.try_get("status")?,
+                erp_sales_order_number: row.try_get("erp_sales_order_number")?,
+                erp_purchase_order_number: row.try_get("erp_purchase_order_number")?,
             })),
             None => Ok(None),
         }
@@ -245,7 +264,8 @@ impl MarketplaceRepository {
         let offset = offset.unwrap_or(0);
 
         let rows = query(
-            "SELECT id, inquiry_id, seller_id, buyer_id, quantity, unit_price, total_price, transaction_date, status 
+            "SELECT id, inquiry_id, seller_id, buyer_id, quantity, unit_price, total_price, transaction_date, status,
+                    erp_sales_order_number, erp_purchase_order_number
              FROM transactions WHERE seller_id = $1 OR buyer_id = $1 ORDER BY transaction_date DESC LIMIT $2 OFFSET $3"
         )
         .bind(user_id)
@@ -266,6 +286,8 @@ impl MarketplaceRepository {
                 total_price: row.try_get("total_price")?,
                 transaction_date: row.try_get("transaction_date")?,
                 status: row.try_get("status")?,
+                erp_sales_order_number: row.try_get("erp_sales_order_number")?,
+                erp_purchase_order_number: row.try_get("erp_purchase_order_number")?,
             });
         }
 
@@ -277,7 +299,8 @@ impl MarketplaceRepository {
             r#"
             UPDATE transactions SET status = $1
             WHERE id = $2
-            RETURNING id, inquiry_id, seller_id, buyer_id, quantity, unit_price, total_price, transaction_date, status
+            RETURNING id, inquiry_id, seller_id, buyer_id, quantity, unit_price, total_price, transaction_date, status,
+                      erp_sales_order_number, erp_purchase_order_number
             "#
         )
         .bind(status)
@@ -295,6 +318,8 @@ impl MarketplaceRepository {
             total_price: row.try_get("total_price")?,
             transaction_date: row.try_get("transaction_date")?,
             status: row.try_get("status")?,
+            erp_sales_order_number: row.try_get("erp_sales_order_number")?,
+            erp_purchase_order_number: row.try_get("erp_purchase_order_number")?,
         })
     }
 
@@ -2,9 +2,15 @@ pub mod user_repo;
 pub mod pharma_repo;
 pub mod inventory_repo;
 pub mod marketplace_repo;
+pub(crate) mod catalog_upsert;
 pub mod openfda_repo;
 pub mod ema_repo;
 pub mod inquiry_message_repo;
+pub mod openfda_recall_repo;
+pub mod regulator_catalog_repo;
+pub mod sync_source_repo;
+pub mod federated_search_repo;
+pub mod semantic_search_repo;
 
 pub use user_repo::*;
 pub use pharma_repo::*;
@@ -12,4 +18,9 @@ pub use inventory_repo::*;
 pub use marketplace_repo::*;
 pub use openfda_repo::*;
 pub use ema_repo::*;
-pub use inquiry_message_repo::*;
\ No newline at end of file
+pub use inquiry_message_repo::*;
+pub use openfda_recall_repo::*;
+pub use regulator_catalog_repo::*;
+pub use sync_source_repo::*;
+pub use federated_search_repo::*;
+pub use semantic_search_repo::*;
\ No newline at end of file
@@ -0,0 +1,180 @@
+use sqlx::{PgPool, query_as, query_scalar};
+use chrono::NaiveDate;
+use crate::models::openfda::OpenFdaCatalogEntry;
+use crate::models::openfda_recall::{normalize_classification, OpenFdaRecall, RecallSearchRequest};
+use crate::middleware::error_handling::Result;
+
+pub struct OpenFdaRecallRepository {
+    pool: PgPool,
+}
+
+impl OpenFdaRecallRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Upsert by recall number. Returns true when the recall was inserted.
+    pub async fn upsert(&self, recall: &OpenFdaRecall) -> Result<bool> {
+        let inserted: bool = query_scalar(
+            r#"
+            INSERT INTO openfda_recalls (
+                recall_number, event_id, status, classification, product_type,
+                product_description, reason_for_recall, code_info, product_quantity,
+                distribution_pattern, recalling_firm, city, state, country,
+                voluntary_mandated, initial_firm_notification, recall_initiation_date,
+                report_date, center_classification_date, termination_date,
+                product_ndcs, package_ndcs, openfda_data, last_synced_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23, NOW())
+            ON CONFLICT (recall_number) DO UPDATE SET
+                event_id = EXCLUDED.event_id,
+                status = EXCLUDED.status,
+                classification = EXCLUDED.classification,
+                product_type = EXCLUDED.product_type,
+                product_description = EXCLUDED.product_description,
+                reason_for_recall = EXCLUDED.reason_for_recall,
+                code_info = EXCLUDED.code_info,
+                product_quantity = EXCLUDED.product_quantity,
+                distribution_pattern = EXCLUDED.distribution_pattern,
+                recalling_firm = EXCLUDED.recalling_firm,
+                city = EXCLUDED.city,
+                state = EXCLUDED.state,
+                country = EXCLUDED.country,
+                voluntary_mandated = EXCLUDED.voluntary_mandated,
+                initial_firm_notification = EXCLUDED.initial_firm_notification,
+                recall_initiation_date = EXCLUDED.recall_initiation_date,
+                report_date = EXCLUDED.report_date,
+                center_classification_date = EXCLUDED.center_classification_date,
+                termination_date = EXCLUDED.termination_date,
+                product_ndcs = EXCLUDED.product_ndcs,
+                package_ndcs = EXCLUDED.package_ndcs,
+                openfda_data = EXCLUDED.openfda_data,
+                last_synced_at = NOW(),
+                updated_at = NOW()
+            RETURNING (xmax = 0) AS inserted
+            "#
+        )
+        .bind(&recall.recall_number)
+        .bind(&recall.event_id)
+        .bind(&recall.status)
+        .bind(&recall.classification)
+        .bind(&recall.product_type)
+        .bind(&recall.product_description)
+        .bind(&recall.reason_for_recall)
+        .bind(&recall.code_info)
+        .bind(&recall.product_quantity)
+        .bind(&recall.distribution_pattern)
+        .bind(&recall.recalling_firm)
+        .bind(&recall.city)
+        .bind(&recall.state)
+        .bind(&recall.country)
+        .bind(&recall.voluntary_mandated)
+        .bind(&recall.initial_firm_notification)
+        .bind(recall.recall_initiation_date)
+        .bind(recall.report_date)
+        .bind(recall.center_classification_date)
+        .bind(recall.termination_date)
+        .bind(&recall.product_ndcs)
+        .bind(&recall.package_ndcs)
+        .bind(&recall.openfda_data)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(inserted)
+    }
+
+    pub async fn search(&self, request: &RecallSearchRequest) -> Result<Vec<OpenFdaRecall>> {
+        let limit = request.limit.unwrap_or(20).clamp(1, 100);
+        let offset = request.offset.unwrap_or(0).max(0);
+        let ndc = request.ndc.as_deref().map(str::trim).filter(|n| !n.is_empty());
+
+        let recalls = query_as::<_, OpenFdaRecall>(
+            r#"
+            SELECT * FROM openfda_recalls
+            WHERE ($1::TEXT IS NULL
+                   OR product_description ILIKE $1
+                   OR reason_for_recall ILIKE $1
+                   OR recall_number ILIKE $1)
+              AND ($2::TEXT IS NULL OR $2 = ANY(product_ndcs) OR $2 = ANY(package_ndcs))
+              AND ($3::TEXT IS NULL OR recalling_firm ILIKE $3)
+              AND ($4::TEXT IS NULL OR classification = $4)
+              AND ($5::TEXT IS NULL OR LOWER(status) = LOWER($5))
+              AND ($6::DATE IS NULL OR report_date >= $6)
+            ORDER BY report_date DESC NULLS LAST, recall_number
+            LIMIT $7 OFFSET $8
+            "#
+        )
+        .bind(request.query.as_ref().map(|q| format!("%{}%", q.trim())))
+        .bind(ndc)
+        .bind(request.recalling_firm.as_ref().map(|f| format!("%{}%", f.trim())))
+        .bind(request.classification.as_deref().and_then(normalize_classification))
+        .bind(request.status.as_deref().map(str::trim))
+        .bind(request.reported_after)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(recalls)
+    }
+
+    pub async fn find_by_recall_number(&self, recall_number: &str) -> Result<Option<OpenFdaRecall>> {
+        let recall = query_as::<_, OpenFdaRecall>(
+            "SELECT * FROM openfda_recalls WHERE recall_number = $1"
+        )
+        .bind(recall_number.trim())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(recall)
+    }
+
+    /// Recalls affecting any of the given product NDCs (e.g. all NDCs held in inventory)
+    pub async fn find_by_product_ndcs(&self, product_ndcs: &[String]) -> Result<Vec<OpenFdaRecall>> {
+        let recalls = query_as::<_, OpenFdaRecall>(
+            r#"
+            SELECT * FROM openfda_recalls
+            WHERE product_ndcs && $1
+            ORDER BY report_date DESC NULLS LAST
+            "#
+        )
+        .bind(product_ndcs)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(recalls)
+    }
+
+    /// Which of the product NDCs are in the OpenFDA catalog
+    pub async fn catalog_product_ndcs(&self, product_ndcs: &[String]) -> Result<Vec<String>> {
+        let known = query_scalar(
+            "SELECT product_ndc FROM openfda_catalog WHERE product_ndc = ANY($1) ORDER BY product_ndc"
+        )
+        .bind(product_ndcs)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(known)
+    }
+
+    /// Catalog entries a recall's NDCs resolve to
+    pub async fn catalog_matches(&self, recall: &OpenFdaRecall) -> Result<Vec<OpenFdaCatalogEntry>> {
+        let entries = query_as::<_, OpenFdaCatalogEntry>(
+            "SELECT * FROM openfda_catalog WHERE product_ndc = ANY($1) ORDER BY product_ndc"
+        )
+        .bind(&recall.product_ndcs)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(entries)
+    }
+
+    /// Latest report date already stored (incremental syncs start from here)
+    pub async fn latest_report_date(&self) -> Result<Option<NaiveDate>> {
+        let date = query_scalar("SELECT MAX(report_date) FROM openfda_recalls")
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(date)
+    }
+}
@@ -1,8 +1,13 @@
-use sqlx::{PgPool, query, query_as, Row};
+use sqlx::{PgPool, Postgres, QueryBuilder, query, query_as, query_scalar, Row};
+use std::time::Instant;
 use uuid::Uuid;
-use chrono::Utc;
-use crate::models::openfda::{OpenFdaCatalogEntry, OpenFdaSyncLog, OpenFdaSearchRequest};
+use chrono::{DateTime, Utc};
+use crate::models::openfda::{
+    DeltaCheckpoint, OpenFdaCatalogEntry, OpenFdaCatalogVersion, OpenFdaSyncLog, OpenFdaSearchRequest, SyncResumeState
+};
+use crate::models::catalog_export::OpenFdaExportParams;
 use crate::middleware::error_handling::{Result, AppError};
+use crate::repositories::catalog_upsert::{dedupe_last, record_throughput, text_array_json, UPSERT_CHUNK_SIZE};
 
 pub struct OpenFdaRepository {
     pool: PgPool,
@@ -13,6 +18,10 @@ impl OpenFdaRepository {
         Self { pool }
     }
 
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+
     /// Insert or update a catalog entry
     pub async fn upsert_entry(&self, entry: &OpenFdaCatalogEntry) -> Result<OpenFdaCatalogEntry> {
         let row = query_as::<_, OpenFdaCatalogEntry>(
@@ -74,79 +83,110 @@ impl OpenFdaRepository {
         Ok(row)
     }
 
-    /// Batch upsert multiple entries
+    /// Batch upsert multiple entries, one `UNNEST` statement per chunk.
+    /// Returns (inserted, updated).
     pub async fn batch_upsert(&self, entries: Vec<OpenFdaCatalogEntry>) -> Result<(i32, i32)> {
         let mut inserted = 0;
         let mut updated = 0;
 
-        for entry in entries {
-            let result = query(
-                r#"
-                INSERT INTO openfda_catalog (
-                    product_ndc, product_id, brand_name, brand_name_base, generic_name,
-                    labeler_name, dosage_form, route, strength, active_ingredients,
-                    product_type, marketing_category, pharm_class, dea_schedule,
-                    packaging, finished, marketing_start_date, listing_expiration_date,
-                    openfda_data, last_synced_at
-                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20)
-                ON CONFLICT (product_ndc) DO UPDATE SET
-                    product_id = EXCLUDED.product_id,
-                    brand_name = EXCLUDED.brand_name,
-                    brand_name_base = EXCLUDED.brand_name_base,
-                    generic_name = EXCLUDED.generic_name,
-                    labeler_name = EXCLUDED.labeler_name,
-                    dosage_form = EXCLUDED.dosage_form,
-                    route = EXCLUDED.route,
-                    strength = EXCLUDED.strength,
-                    active_ingredients = EXCLUDED.active_ingredients,
-                    product_type = EXCLUDED.product_type,
-                    marketing_category = EXCLUDED.marketing_category,
-                    pharm_class = EXCLUDED.pharm_class,
-                    dea_schedule = EXCLUDED.dea_schedule,
-                    packaging = EXCLUDED.packaging,
-                    finished = EXCLUDED.finished,
-                    marketing_start_date = EXCLUDED.marketing_start_date,
-                    listing_expiration_date = EXCLUDED.listing_expiration_date,
-                    openfda_data = EXCLUDED.openfda_data,
-                    last_synced_at = EXCLUDED.last_synced_at,
-                    updated_at = CURRENT_TIMESTAMP
-                RETURNING (xmax = 0) AS was_inserted
-                "#
-            )
-            .bind(&entry.product_ndc)
-            .bind(&entry.product_id)
-            .bind(&entry.brand_name)
-            .bind(&entry.brand_name_base)
-            .bind(&entry.generic_name)
-            .bind(&entry.labeler_name)
-            .bind(&entry.dosage_form)
-            .bind(&entry.route)
-            .bind(&entry.strength)
-            .bind(&entry.active_ingredients)
-            .bind(&entry.product_type)
-            .bind(&entry.marketing_category)
-            .bind(&entry.pharm_class)
-            .bind(&entry.dea_schedule)
-            .bind(&entry.packaging)
-            .bind(&entry.finished)
-            .bind(&entry.marketing_start_date)
-            .bind(&entry.listing_expiration_date)
-            .bind(&entry.openfda_data)
-            .bind(&entry.last_synced_at)
-            .fetch_one(&self.pool)
-            .await?;
+        for chunk in entries.chunks(UPSERT_CHUNK_SIZE) {
+            let started = Instant::now();
+            let (chunk_inserted, chunk_updated) = self.upsert_chunk(chunk).await?;
+            record_throughput("openfda", chunk.len(), started.elapsed());
 
-            let was_inserted: bool = result.try_get("was_inserted")?;
-            if was_inserted {
-                inserted += 1;
-            } else {
-                updated += 1;
-            }
+            inserted += chunk_inserted;
+            updated += chunk_updated;
         }
 
         Ok((inserted, updated))
     }
 
+    async fn upsert_chunk(&self, chunk: &[OpenFdaCatalogEntry]) -> Result<(i32, i32)> {
+        let (entries, duplicates) = dedupe_last(chunk, |entry| entry.product_ndc.clone());
+
+        let was_inserted: Vec<bool> = query_scalar(
+            r#"
+            INSERT INTO openfda_catalog (
+                product_ndc, product_id, brand_name, brand_name_base, generic_name,
+                labeler_name, dosage_form, route, strength, active_ingredients,
+                product_type, marketing_category, pharm_class, dea_schedule,
+                packaging, finished, marketing_start_date, listing_expiration_date,
+                openfda_data, last_synced_at
+            )
+            SELECT
+                t.product_ndc, t.product_id, t.brand_name, t.brand_name_base, t.generic_name,
+                t.labeler_name, t.dosage_form,
+                CASE WHEN t.route IS NULL THEN NULL ELSE ARRAY(SELECT jsonb_array_elements_text(t.route)) END,
+                t.strength, t.active_ingredients, t.product_type, t.marketing_category,
+                CASE WHEN t.pharm_class IS NULL THEN NULL ELSE ARRAY(SELECT jsonb_array_elements_text(t.pharm_class)) END,
+                t.dea_schedule, t.packaging, t.finished, t.marketing_start_date, t.listing_expiration_date,
+                t.openfda_data, t.last_synced_at
+            FROM UNNEST(
+                $1::text[], $2::text[], $3::text[], $4::text[], $5::text[],
+                $6::text[], $7::text[], $8::jsonb[], $9::text[], $10::jsonb[],
+                $11::text[], $12::text[], $13::jsonb[], $14::text[],
+                $15::jsonb[], $16::bool[], $17::date[], $18::date[],
+                $19::jsonb[], $20::timestamptz[]
+            ) AS t(
+                product_ndc, product_id, brand_name, brand_name_base, generic_name,
+                labeler_name, dosage_form, route, strength, active_ingredients,
+                product_type, marketing_category, pharm_class, dea_schedule,
+                packaging, finished, marketing_start_date, listing_expiration_date,
+                openfda_data, last_synced_at
+            )
+            ON CONFLICT (product_ndc) DO UPDATE SET
+                product_id = EXCLUDED.product_id,
+                brand_name = EXCLUDED.brand_name,
+                brand_name_base = EXCLUDED.brand_name_base,
+                generic_name = EXCLUDED.generic_name,
+                labeler_name = EXCLUDED.labeler_name,
+                dosage_form = EXCLUDED.dosage_form,
+                route = EXCLUDED.route,
+                strength = EXCLUDED.strength,
+                active_ingredients = EXCLUDED.active_ingredients,
+                product_type = EXCLUDED.product_type,
+                marketing_category = EXCLUDED.marketing_category,
+                pharm_class = EXCLUDED.pharm_class,
+                dea_schedule = EXCLUDED.dea_schedule,
+                packaging = EXCLUDED.packaging,
+                finished = EXCLUDED.finished,
+                marketing_start_date = EXCLUDED.marketing_start_date,
+                listing_expiration_date = EXCLUDED.listing_expiration_date,
+                openfda_data = EXCLUDED.openfda_data,
+                last_synced_at = EXCLUDED.last_synced_at,
+                updated_at = CURRENT_TIMESTAMP
+            RETURNING (xmax = 0) AS was_inserted
+            "#
+        )
+        .bind(entries.iter().map(|e| e.product_ndc.clone()).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.product_id.clone()).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.brand_name.clone()).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.brand_name_base.clone()).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.generic_name.clone()).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.labeler_name.clone()).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.dosage_form.clone()).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| text_array_json(&e.route)).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.strength.clone()).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.active_ingredients.clone()).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.product_type.clone()).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.marketing_category.clone()).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| text_array_json(&e.pharm_class)).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.dea_schedule.clone()).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.packaging.clone()).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.finished).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.marketing_start_date).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.listing_expiration_date).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.openfda_data.clone()).collect::<Vec<_>>())
+        .bind(entries.iter().map(|e| e.last_synced_at).collect::<Vec<_>>())
+        .fetch_all(&self.pool)
+        .await?;
+
+        let inserted = was_inserted.iter().filter(|inserted| **inserted).count() as i32;
+        let updated = was_inserted.len() as i32 - inserted + duplicates;
+
+        Ok((inserted, updated))
+    }
+
     /// Search catalog with full-text search
     pub async fn search(&self, request: &OpenFdaSearchRequest) -> Result<Vec<OpenFdaCatalogEntry>> {
         let limit = request.limit.unwrap_or(20).min(100);
@@ -203,6 +243,122 @@ impl OpenFdaRepository {
         Ok(entry)
     }
 
+    /// All recorded versions of an NDC entry, newest first
+    pub async fn get_history(&self, ndc: &str) -> Result<Vec<OpenFdaCatalogVersion>> {
+        let versions = query_as::<_, OpenFdaCatalogVersion>(
+            r#"
+            SELECT * FROM openfda_catalog_history
+            WHERE product_ndc = $1
+            ORDER BY version DESC
+            "#
+        )
+        .bind(ndc)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(versions)
+    }
+
+    /// The version of an NDC entry that was in force at the given time
+    pub async fn find_version_at(&self, ndc: &str, at: DateTime<Utc>) -> Result<Option<OpenFdaCatalogVersion>> {
+        let version = query_as::<_, OpenFdaCatalogVersion>(
+            r#"
+            SELECT * FROM openfda_catalog_history
+            WHERE product_ndc = $1
+              AND valid_from <= $2
+              AND (valid_to IS NULL OR valid_to > $2)
+            ORDER BY version DESC
+            LIMIT 1
+            "#
+        )
+        .bind(ndc)
+        .bind(at)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(version)
+    }
+
+    /// Find every stored entry among the given NDC codes
+    pub async fn find_by_ndcs(&self, ndcs: &[String]) -> Result<Vec<OpenFdaCatalogEntry>> {
+        let entries = query_as::<_, OpenFdaCatalogEntry>(
+            "SELECT * FROM openfda_catalog WHERE product_ndc = ANY($1)"
+        )
+        .bind(ndcs)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(entries)
+    }
+
+    /// (product NDC, ATC code) of the given NDCs, through their RxNorm mapping
+    pub async fn find_atc_codes(&self, product_ndcs: &[String]) -> Result<Vec<(String, String)>> {
+        let codes = query_as::<_, (String, String)>(
+            r#"
+            SELECT DISTINCT o.product_ndc, m.atc_code
+            FROM openfda_catalog o
+            JOIN rxnorm_atc_mapping m ON o.openfda_data->'rxcui' @> to_jsonb(m.rxcui)
+            WHERE o.product_ndc = ANY($1)
+            ORDER BY o.product_ndc, m.atc_code
+            "#
+        )
+        .bind(product_ndcs)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(codes)
+    }
+
+    /// Entries whose RxNorm mapping has an ATC code starting with `atc_prefix`
+    pub async fn find_by_atc_prefix(&self, atc_prefix: &str, limit: i64, offset: i64) -> Result<Vec<OpenFdaCatalogEntry>> {
+        let entries = query_as::<_, OpenFdaCatalogEntry>(
+            r#"
+            SELECT o.* FROM openfda_catalog o
+            WHERE EXISTS (
+                SELECT 1 FROM rxnorm_atc_mapping m
+                WHERE m.atc_code LIKE $1 || '%'
+                  AND o.openfda_data->'rxcui' @> to_jsonb(m.rxcui))
+            ORDER BY o.brand_name ASC, o.product_ndc ASC
+            LIMIT $2 OFFSET $3
+            "#
+        )
+        .bind(atc_prefix)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(entries)
+    }
+
+    /// Number of entries an export with these filters covers
+    pub async fn count_export(&self, params: &OpenFdaExportParams) -> Result<i64> {
+        let mut builder = QueryBuilder::<Postgres>::new("SELECT COUNT(*) FROM openfda_catalog");
+        push_export_filters(&mut builder, params, params.after.as_deref());
+
+        let count: i64 = builder.build_query_scalar::<i64>().fetch_one(&self.pool).await?;
+        Ok(count)
+    }
+
+    /// A page of an export in product NDC order, starting after `after` and skipping `offset` rows
+    pub async fn export_page(
+        &self,
+        params: &OpenFdaExportParams,
+        after: Option<&str>,
+        offset: i64,
+        limit: i64,
+    ) -> Result<Vec<OpenFdaCatalogEntry>> {
+        let mut builder = QueryBuilder::<Postgres>::new("SELECT * FROM openfda_catalog");
+        push_export_filters(&mut builder, params, after);
+        builder.push(" ORDER BY product_ndc LIMIT ");
+        builder.push_bind(limit);
+        builder.push(" OFFSET ");
+        builder.push_bind(offset);
+
+        let entries = builder.build_query_as::<OpenFdaCatalogEntry>().fetch_all(&self.pool).await?;
+        Ok(entries)
+    }
+
     /// Get total count
     pub async fn get_total_count(&self) -> Result<i64> {
         let row = query("SELECT COUNT(*) as count FROM openfda_catalog")
@@ -280,12 +436,26 @@ impl OpenFdaRepository {
         Ok(())
     }
 
+    /// Mark a completed run as suspect. Returns false if the run is not (or no longer) completed.
+    pub async fn mark_sync_suspect(&self, log_id: Uuid, anomalies: &serde_json::Value) -> Result<bool> {
+        let result = query(
+            "UPDATE openfda_sync_log SET status = 'suspect', anomalies = $2 WHERE id = $1 AND status = 'completed'"
+        )
+        .bind(log_id)
+        .bind(anomalies)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
     /// Get last successful sync
     pub async fn get_last_successful_sync(&self) -> Result<Option<OpenFdaSyncLog>> {
         let log = query_as::<_, OpenFdaSyncLog>(
             r#"
             SELECT * FROM openfda_sync_log
             WHERE status = 'completed'
+              AND COALESCE(sync_type, 'full') NOT IN ('device', 'recall')
             ORDER BY sync_completed_at DESC
             LIMIT 1
             "#
@@ -304,9 +474,9 @@ impl OpenFdaRepository {
             INSERT INTO openfda_sync_log (
                 sync_started_at, status, sync_type, total_expected, total_batches,
                 records_processed, records_inserted, records_updated, records_skipped, records_failed,
-                current_batch
+                current_batch, last_heartbeat_at
             )
-            VALUES ($1, 'in_progress', $2, $3, $4, 0, 0, 0, 0, 0, 0)
+            VALUES ($1, 'in_progress', $2, $3, $4, 0, 0, 0, 0, 0, 0, $1)
             RETURNING id
             "#
         )
@@ -342,7 +512,8 @@ impl OpenFdaRepository {
                 records_skipped = $5,
                 records_failed = $6,
                 current_batch = $7,
-                api_response_time_ms = COALESCE(api_response_time_ms, 0) + $8
+                api_response_time_ms = COALESCE(api_response_time_ms, 0) + $8,
+                last_heartbeat_at = NOW()
             WHERE id = $1
             "#
         )
@@ -401,7 +572,8 @@ impl OpenFdaRepository {
                 records_failed = $6,
                 records_processed = $2,
                 processing_time_ms = $7,
-                status = 'completed'
+                status = 'completed',
+                resume_state = NULL
             WHERE id = $8
             "#
         )
@@ -419,6 +591,170 @@ impl OpenFdaRepository {
         Ok(())
     }
 
+    /// Store the delta checkpoint of a completed sync
+    pub async fn save_checkpoint(&self, log_id: Uuid, checkpoint: &DeltaCheckpoint) -> Result<()> {
+        query("UPDATE openfda_sync_log SET checkpoint = $1 WHERE id = $2")
+            .bind(serde_json::to_value(checkpoint)?)
+            .bind(log_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Latest checkpoint from a completed drug catalog sync
+    pub async fn get_latest_checkpoint(&self) -> Result<Option<DeltaCheckpoint>> {
+        let row = query(
+            r#"
+            SELECT checkpoint FROM openfda_sync_log
+            WHERE status = 'completed'
+              AND checkpoint IS NOT NULL
+              AND COALESCE(sync_type, 'full') NOT IN ('device', 'recall')
+            ORDER BY sync_completed_at DESC
+            LIMIT 1
+            "#
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match row {
+            Some(row) => {
+                let value: serde_json::Value = row.try_get("checkpoint")?;
+                Ok(serde_json::from_value(value).ok())
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Persist the resume position of an unfinished sync (also counts as a heartbeat)
+    pub async fn save_resume_state(&self, log_id: Uuid, state: &SyncResumeState) -> Result<()> {
+        query("UPDATE openfda_sync_log SET resume_state = $1, last_heartbeat_at = NOW() WHERE id = $2")
+            .bind(serde_json::to_value(state)?)
+            .bind(log_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Record that a running sync is alive, e.g. before it waits out a rate limit
+    pub async fn touch_heartbeat(&self, log_id: Uuid) -> Result<()> {
+        query("UPDATE openfda_sync_log SET last_heartbeat_at = NOW() WHERE id = $1")
+            .bind(log_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_resume_state(&self, log_id: Uuid) -> Result<Option<SyncResumeState>> {
+        let value: Option<serde_json::Value> = query_scalar(
+            "SELECT resume_state FROM openfda_sync_log WHERE id = $1"
+        )
+        .bind(log_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .flatten();
+
+        Ok(value.and_then(|v| serde_json::from_value(v).ok()))
+    }
+
+    /// Move a failed or interrupted (by a shutdown, or stale in_progress) sync back to in_progress.
+    /// Returns false if the sync is not resumable; the status check makes concurrent resumes safe.
+    pub async fn mark_resumed(&self, log_id: Uuid) -> Result<bool> {
+        let result = query(
+            r#"
+            UPDATE openfda_sync_log
+            SET status = 'in_progress',
+                error_message = NULL,
+                sync_completed_at = NULL,
+                last_heartbeat_at = NOW(),
+                resume_count = resume_count + 1
+            WHERE id = $1
+              AND resume_state IS NOT NULL
+              AND cancelled_at IS NULL
+              AND (status IN ('failed', 'interrupted')
+                   OR (status = 'in_progress'
+                       AND COALESCE(last_heartbeat_at, sync_started_at) < NOW() - INTERVAL '1 minute' * $2))
+            "#
+        )
+        .bind(log_id)
+        .bind(OpenFdaSyncLog::STALE_AFTER_MINUTES as i32)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Move a sync back to in_progress for another attempt of its background job. Only the
+    /// job holding the lease calls this, so unlike `mark_resumed` it doesn't wait for the
+    /// sync to go stale. Returns false if the sync was cancelled or has finished.
+    pub async fn restart_sync_log(&self, log_id: Uuid) -> Result<bool> {
+        let result = query(
+            r#"
+            UPDATE openfda_sync_log
+            SET status = 'in_progress',
+                error_message = NULL,
+                sync_completed_at = NULL,
+                last_heartbeat_at = NOW(),
+                resume_count = resume_count + CASE WHEN resume_state IS NULL THEN 0 ELSE 1 END
+            WHERE id = $1
+              AND cancelled_at IS NULL
+              AND status IN ('in_progress', 'failed', 'interrupted')
+            "#
+        )
+        .bind(log_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Most recent interrupted drug catalog sync that has a resume checkpoint
+    pub async fn find_resumable_sync(&self) -> Result<Option<Uuid>> {
+        let id = query_scalar(
+            r#"
+            SELECT id FROM openfda_sync_log
+            WHERE resume_state IS NOT NULL
+              AND cancelled_at IS NULL
+              AND (status IN ('failed', 'interrupted')
+                   OR (status = 'in_progress'
+                       AND COALESCE(last_heartbeat_at, sync_started_at) < NOW() - INTERVAL '1 minute' * $1))
+            ORDER BY sync_started_at DESC
+            LIMIT 1
+            "#
+        )
+        .bind(OpenFdaSyncLog::STALE_AFTER_MINUTES as i32)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    /// NDCs from `ndcs` that were upserted at or after `since` (confirms bloom filter hits)
+    pub async fn filter_synced_since(&self, ndcs: &[String], since: DateTime<Utc>) -> Result<Vec<String>> {
+        let synced = query_scalar(
+            "SELECT product_ndc FROM openfda_catalog WHERE product_ndc = ANY($1) AND last_synced_at >= $2"
+        )
+        .bind(ndcs)
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(synced)
+    }
+
+    /// Record the sync type actually used (delta syncs may fall back to full)
+    pub async fn set_sync_type(&self, log_id: Uuid, sync_type: &str) -> Result<()> {
+        query("UPDATE openfda_sync_log SET sync_type = $1 WHERE id = $2")
+            .bind(sync_type)
+            .bind(log_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
     /// Get sync log by ID
     pub async fn get_sync_log(&self, log_id: Uuid) -> Result<Option<OpenFdaSyncLog>> {
         let log = query_as::<_, OpenFdaSyncLog>(
@@ -485,11 +821,18 @@ impl OpenFdaRepository {
         Ok(result.rows_affected() > 0)
     }
 
-    /// Check if there's an active sync running
+    /// Check if there's an active sync running (interrupted syncs without a recent heartbeat don't count)
     pub async fn is_sync_running(&self) -> Result<bool> {
         let row = query(
-            "SELECT EXISTS(SELECT 1 FROM openfda_sync_log WHERE status = 'in_progress') as running"
+            r#"
+            SELECT EXISTS(
+                SELECT 1 FROM openfda_sync_log
+                WHERE status = 'in_progress'
+                  AND COALESCE(last_heartbeat_at, sync_started_at) >= NOW() - INTERVAL '1 minute' * $1
+            ) as running
+            "#
         )
+        .bind(OpenFdaSyncLog::STALE_AFTER_MINUTES as i32)
         .fetch_one(&self.pool)
         .await?;
 
@@ -513,3 +856,35 @@ impl OpenFdaRepository {
         Ok(result.rows_affected() as i64)
     }
 }
+
+fn push_export_filters(builder: &mut QueryBuilder<Postgres>, params: &OpenFdaExportParams, after: Option<&str>) {
+    builder.push(" WHERE TRUE");
+    if let Some(manufacturer) = params.manufacturer.as_deref().filter(|m| !m.trim().is_empty()) {
+        builder.push(" AND labeler_name ILIKE ");
+        builder.push_bind(format!("%{}%", manufacturer.trim()));
+    }
+    if let Some(product_type) = &params.product_type {
+        builder.push(" AND product_type ILIKE ");
+        builder.push_bind(product_type.clone());
+    }
+    if let Some(marketing_category) = &params.marketing_category {
+        builder.push(" AND marketing_category ILIKE ");
+        builder.push_bind(marketing_category.clone());
+    }
+    if let Some(dosage_form) = &params.dosage_form {
+        builder.push(" AND dosage_form ILIKE ");
+        builder.push_bind(dosage_form.clone());
+    }
+    if let Some(dea_schedule) = &params.dea_schedule {
+        builder.push(" AND dea_schedule = ");
+        builder.push_bind(dea_schedule.clone());
+    }
+    if let Some(updated_since) = params.updated_since {
+        builder.push(" AND updated_at >= ");
+        builder.push_bind(updated_since);
+    }
+    if let Some(after) = after {
+        builder.push(" AND product_ndc > ");
+        builder.push_bind(after.to_string());
+    }
+}
@@ -1,7 +1,10 @@
-use sqlx::{PgPool, query, Row};
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, query, query_as, Row};
 use uuid::Uuid;
 use crate::models::pharmaceutical::{Pharmaceutical, CreatePharmaceuticalRequest, SearchPharmaceuticalRequest};
-use crate::middleware::error_handling::Result;
+use crate::models::ingredient::{Allergen, ParsedIngredient, PharmaceuticalIngredient};
+use crate::models::summary_aggregate::Summarized;
+use crate::middleware::error_handling::{Result, AppError};
 
 pub struct PharmaceuticalRepository {
     pool: PgPool,
@@ -15,9 +18,9 @@ impl PharmaceuticalRepository {
     pub async fn create(&self, request: &CreatePharmaceuticalRequest) -> Result<Pharmaceutical> {
         let row = query(
             r#"
-            INSERT INTO pharmaceuticals (brand_name, generic_name, ndc_code, manufacturer, category, description, strength, dosage_form, storage_requirements)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
-            RETURNING id, brand_name, generic_name, ndc_code, manufacturer, category, description, strength, dosage_form, storage_requirements, created_at
+            INSERT INTO pharmaceuticals (brand_name, generic_name, ndc_code, manufacturer, category, description, strength, dosage_form, storage_requirements, product_domain, udi_di, device_class, target_species)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            RETURNING id, brand_name, generic_name, ndc_code, manufacturer, category, description, strength, dosage_form, storage_requirements, product_domain, udi_di, device_class, target_species, created_at
             "#
         )
         .bind(&request.brand_name)
@@ -29,6 +32,10 @@ impl PharmaceuticalRepository {
         .bind(&request.strength)
         .bind(&request.dosage_form)
         .bind(&request.storage_requirements)
+        .bind(request.product_domain.unwrap_or_default().as_str())
+        .bind(&request.udi_di)
+        .bind(&request.device_class)
+        .bind(&request.target_species)
         .fetch_one(&self.pool)
         .await?;
 
@@ -43,13 +50,17 @@ impl PharmaceuticalRepository {
             strength: row.try_get("strength")?,
             dosage_form: row.try_get("dosage_form")?,
             storage_requirements: row.try_get("storage_requirements")?,
+            product_domain: row.try_get("product_domain")?,
+            udi_di: row.try_get("udi_di")?,
+            device_class: row.try_get("device_class")?,
+            target_species: row.try_get("target_species")?,
             created_at: row.try_get("created_at")?,
         })
     }
 
     pub async fn find_by_id(&self, id: Uuid) -> Result<Option<Pharmaceutical>> {
         let row = query(
-            "SELECT id, brand_name, generic_name, ndc_code, manufacturer, category, description, strength, dosage_form, storage_requirements, created_at FROM pharmaceuticals WHERE id = $1"
+            "SELECT id, brand_name, generic_name, ndc_code, manufacturer, category, description, strength, dosage_form, storage_requirements, product_domain, udi_di, device_class, target_species, created_at FROM pharmaceuticals WHERE id = $1"
         )
         .bind(id)
         .fetch_optional(&self.pool)
@@ -67,15 +78,31 @@ impl PharmaceuticalRepository {
                 strength: row.try_get("strength")?,
                 dosage_form: row.try_get("dosage_form")?,
                 storage_requirements: row.try_get("storage_requirements")?,
+                product_domain: row.try_get("product_domain")?,
+                udi_di: row.try_get("udi_di")?,
+                device_class: row.try_get("device_class")?,
+                target_species: row.try_get("target_species")?,
                 created_at: row.try_get("created_at")?,
             })),
             None => Ok(None),
         }
     }
 
+    /// Pharmaceuticals among the given ids; missing ids are skipped. Order is unspecified.
+    pub async fn find_by_ids(&self, ids: &[Uuid]) -> Result<Vec<Pharmaceutical>> {
+        let pharmaceuticals = query_as::<_, Pharmaceutical>(
+            "SELECT id, brand_name, generic_name, ndc_code, manufacturer, category, description, strength, dosage_form, storage_requirements, product_domain, udi_di, device_class, target_species, created_at FROM pharmaceuticals WHERE id = ANY($1)"
+        )
+        .bind(ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(pharmaceuticals)
+    }
+
     pub async fn find_by_ndc(&self, ndc_code: &str) -> Result<Option<Pharmaceutical>> {
         let row = query(
-            "SELECT id, brand_name, generic_name, ndc_code, manufacturer, category, description, strength, dosage_form, storage_requirements, created_at FROM pharmaceuticals WHERE ndc_code = $1"
+            "SELECT id, brand_name, generic_name, ndc_code, manufacturer, category, description, strength, dosage_form, storage_requirements, product_domain, udi_di, device_class, target_species, created_at FROM pharmaceuticals WHERE ndc_code = $1"
         )
         .bind(ndc_code)
         .fetch_optional(&self.pool)
@@ -93,6 +120,40 @@ impl PharmaceuticalRepository {
                 strength: row.try_get("strength")?,
                 dosage_form: row.try_get("dosage_form")?,
                 storage_requirements: row.try_get("storage_requirements")?,
+                product_domain: row.try_get("product_domain")?,
+                udi_di: row.try_get("udi_di")?,
+                device_class: row.try_get("device_class")?,
+                target_species: row.try_get("target_species")?,
+                created_at: row.try_get("created_at")?,
+            })),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn find_by_udi_di(&self, udi_di: &str) -> Result<Option<Pharmaceutical>> {
+        let row = query(
+            "SELECT id, brand_name, generic_name, ndc_code, manufacturer, category, description, strength, dosage_form, storage_requirements, product_domain, udi_di, device_class, target_species, created_at FROM pharmaceuticals WHERE udi_di = $1"
+        )
+        .bind(udi_di)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match row {
+            Some(row) => Ok(Some(Pharmaceutical {
+                id: row.try_get("id")?,
+                brand_name: row.try_get("brand_name")?,
+                generic_name: row.try_get("generic_name")?,
+                ndc_code: row.try_get("ndc_code")?,
+                manufacturer: row.try_get("manufacturer")?,
+                category: row.try_get("category")?,
+                description: row.try_get("description")?,
+                strength: row.try_get("strength")?,
+                dosage_form: row.try_get("dosage_form")?,
+                storage_requirements: row.try_get("storage_requirements")?,
+                product_domain: row.try_get("product_domain")?,
+                udi_di: row.try_get("udi_di")?,
+                device_class: row.try_get("device_class")?,
+                target_species: row.try_get("target_species")?,
                 created_at: row.try_get("created_at")?,
             })),
             None => Ok(None),
@@ -103,7 +164,7 @@ impl PharmaceuticalRepository {
         let limit = request.limit.unwrap_or(50).min(100);
         let offset = request.offset.unwrap_or(0);
 
-        let mut query_str = "SELECT id, brand_name, generic_name, ndc_code, manufacturer, category, description, strength, dosage_form, storage_requirements, created_at FROM pharmaceuticals WHERE 1=1".to_string();
+        let mut query_str = "SELECT id, brand_name, generic_name, ndc_code, manufacturer, category, description, strength, dosage_form, storage_requirements, product_domain, udi_di, device_class, target_species, created_at FROM pharmaceuticals WHERE 1=1".to_string();
         let mut param_count = 1;
 
         if let Some(ref query_str_param) = request.query {
@@ -136,6 +197,29 @@ impl PharmaceuticalRepository {
             param_count += 1;
         }
 
+        if request.product_domain.is_some() {
+            query_str.push_str(&format!(" AND product_domain = ${}", param_count));
+            param_count += 1;
+        }
+
+        if request.udi_di.is_some() {
+            query_str.push_str(&format!(" AND udi_di = ${}", param_count));
+            param_count += 1;
+        }
+
+        let free_of = match request.free_of {
+            Some(ref filter) => Allergen::parse_filter(filter).map_err(AppError::BadRequest)?,
+            None => Vec::new(),
+        };
+
+        if !free_of.is_empty() {
+            // Only products whose excipients have been declared can be "free of" anything
+            query_str.push_str(&format!(
+                " AND ingredients_declared_at IS NOT NULL AND NOT EXISTS (SELECT 1 FROM pharmaceutical_ingredients pi WHERE pi.pharmaceutical_id = pharmaceuticals.id AND pi.allergens && ${})",
+                param_count
+            ));
+        }
+
         query_str.push_str(" ORDER BY brand_name ASC");
         query_str.push_str(&format!(" LIMIT {} OFFSET {}", limit, offset));
 
@@ -171,6 +255,19 @@ impl PharmaceuticalRepository {
             query_builder = query_builder.bind(ndc_code);
         }
 
+        if let Some(product_domain) = request.product_domain {
+            query_builder = query_builder.bind(product_domain.as_str());
+        }
+
+        if let Some(ref udi_di) = request.udi_di {
+            query_builder = query_builder.bind(udi_di);
+        }
+
+        if !free_of.is_empty() {
+            let keys: Vec<String> = free_of.iter().map(|a| a.as_str().to_string()).collect();
+            query_builder = query_builder.bind(keys);
+        }
+
         let rows = query_builder
             .fetch_all(&self.pool)
             .await?;
@@ -188,6 +285,10 @@ impl PharmaceuticalRepository {
                 strength: row.try_get("strength")?,
                 dosage_form: row.try_get("dosage_form")?,
                 storage_requirements: row.try_get("storage_requirements")?,
+                product_domain: row.try_get("product_domain")?,
+                udi_di: row.try_get("udi_di")?,
+                device_class: row.try_get("device_class")?,
+                target_species: row.try_get("target_species")?,
                 created_at: row.try_get("created_at")?,
             });
         }
@@ -195,6 +296,72 @@ impl PharmaceuticalRepository {
         Ok(pharmaceuticals)
     }
 
+    /// Replace the ingredient list. `excipients_declared` says whether it includes the
+    /// inactive ingredients; without them the product counts as undeclared again.
+    pub async fn set_ingredients(
+        &self,
+        pharmaceutical_id: Uuid,
+        ingredients: &[ParsedIngredient],
+        excipients_declared: bool,
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        query("DELETE FROM pharmaceutical_ingredients WHERE pharmaceutical_id = $1")
+            .bind(pharmaceutical_id)
+            .execute(&mut *tx)
+            .await?;
+
+        for (position, ingredient) in ingredients.iter().enumerate() {
+            let allergens: Vec<String> = ingredient.allergens.iter().map(|a| a.as_str().to_string()).collect();
+
+            query(
+                r#"
+                INSERT INTO pharmaceutical_ingredients (pharmaceutical_id, role, name, strength, allergens, position)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                "#
+            )
+            .bind(pharmaceutical_id)
+            .bind(ingredient.role.as_str())
+            .bind(&ingredient.name)
+            .bind(&ingredient.strength)
+            .bind(allergens)
+            .bind(position as i32)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        query(
+            "UPDATE pharmaceuticals SET ingredients_declared_at = CASE WHEN $2 THEN NOW() ELSE NULL END WHERE id = $1"
+        )
+        .bind(pharmaceutical_id)
+        .bind(excipients_declared)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn get_ingredients(&self, pharmaceutical_id: Uuid) -> Result<Vec<PharmaceuticalIngredient>> {
+        let ingredients = sqlx::query_as::<_, PharmaceuticalIngredient>(
+            "SELECT * FROM pharmaceutical_ingredients WHERE pharmaceutical_id = $1 ORDER BY role, position"
+        )
+        .bind(pharmaceutical_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(ingredients)
+    }
+
+    pub async fn ingredients_declared(&self, pharmaceutical_id: Uuid) -> Result<bool> {
+        let row = query("SELECT ingredients_declared_at IS NOT NULL AS declared FROM pharmaceuticals WHERE id = $1")
+            .bind(pharmaceutical_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.and_then(|r| r.try_get::<bool, _>("declared").ok()).unwrap_or(false))
+    }
+
     pub async fn ndc_exists(&self, ndc_code: &str) -> Result<bool> {
         let row = query("SELECT EXISTS(SELECT 1 FROM pharmaceuticals WHERE ndc_code = $1) as exists")
             .bind(ndc_code)
@@ -204,23 +371,39 @@ impl PharmaceuticalRepository {
         Ok(row.try_get::<bool, _>("exists").unwrap_or(false))
     }
 
-    pub async fn get_manufacturers(&self) -> Result<Vec<String>> {
-        let rows = query("SELECT DISTINCT manufacturer FROM pharmaceuticals ORDER BY manufacturer")
-            .fetch_all(&self.pool)
-            .await?;
+    /// From the `pharma_manufacturer_counts` summary (refreshed shortly after writes)
+    pub async fn get_manufacturers(&self) -> Result<Summarized<Vec<String>>> {
+        let (refreshed_at, manufacturers): (DateTime<Utc>, Vec<String>) = query_as(
+            r#"
+            SELECT r.refreshed_at,
+                   COALESCE(array_agg(m.manufacturer::text ORDER BY m.manufacturer) FILTER (WHERE m.manufacturer IS NOT NULL), '{}')
+            FROM summary_refreshes r
+            LEFT JOIN pharma_manufacturer_counts m ON TRUE
+            WHERE r.view_name = 'pharma_manufacturer_counts'
+            GROUP BY r.refreshed_at
+            "#
+        )
+        .fetch_one(&self.pool)
+        .await?;
 
-        Ok(rows.into_iter()
-            .map(|row| row.try_get::<String, _>("manufacturer"))
-            .collect::<std::result::Result<Vec<String>, _>>()?)
+        Ok(Summarized { value: manufacturers, refreshed_at: Some(refreshed_at) })
     }
 
-    pub async fn get_categories(&self) -> Result<Vec<String>> {
-        let rows = query("SELECT DISTINCT category FROM pharmaceuticals WHERE category IS NOT NULL ORDER BY category")
-            .fetch_all(&self.pool)
-            .await?;
+    /// From the `pharma_category_counts` summary (refreshed shortly after writes)
+    pub async fn get_categories(&self) -> Result<Summarized<Vec<String>>> {
+        let (refreshed_at, categories): (DateTime<Utc>, Vec<String>) = query_as(
+            r#"
+            SELECT r.refreshed_at,
+                   COALESCE(array_agg(c.category::text ORDER BY c.category) FILTER (WHERE c.category IS NOT NULL), '{}')
+            FROM summary_refreshes r
+            LEFT JOIN pharma_category_counts c ON TRUE
+            WHERE r.view_name = 'pharma_category_counts'
+            GROUP BY r.refreshed_at
+            "#
+        )
+        .fetch_one(&self.pool)
+        .await?;
 
-        Ok(rows.into_iter()
-            .filter_map(|row| row.try_get::<Option<String>, _>("category").ok().flatten())
-            .collect())
+        Ok(Summarized { value: categories, refreshed_at: Some(refreshed_at) })
     }
 }
\ No newline at end of file
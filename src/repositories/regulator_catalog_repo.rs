@@ -0,0 +1,370 @@
+use sqlx::{PgPool, query, query_as, query_scalar};
+use uuid::Uuid;
+use chrono::{DateTime, Utc};
+use crate::models::regulator_catalog::{
+    CatalogSyncLog, RegulatorCatalogEntry, RegulatorCatalogRecord,
+    RegulatorCatalogSearchRequest, RegulatorSource
+};
+use crate::middleware::error_handling::Result;
+
+/// Runs without a heartbeat for this long are treated as dead (process restarted mid-sync)
+const STALE_SYNC_MINUTES: i32 = 30;
+
+/// Storage for every regulator catalog source; all queries are scoped by `source`
+pub struct RegulatorCatalogRepository {
+    pool: PgPool,
+}
+
+impl RegulatorCatalogRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    // ============================================================================
+    // Catalog
+    // ============================================================================
+
+    /// Upsert records by (source, source_id). Returns (inserted, updated).
+    pub async fn batch_upsert(
+        &self,
+        source: RegulatorSource,
+        records: &[RegulatorCatalogRecord],
+    ) -> Result<(i32, i32)> {
+        let mut inserted = 0;
+        let mut updated = 0;
+
+        for record in records {
+            let was_inserted: bool = query_scalar(
+                r#"
+                INSERT INTO regulator_catalog (
+                    source, source_id, country, product_name, active_ingredients, strength,
+                    dosage_form, route_of_administration, atc_code, authorization_holder,
+                    authorization_status, authorization_date, raw_data, last_synced_at
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, NOW())
+                ON CONFLICT (source, source_id) DO UPDATE SET
+                    product_name = EXCLUDED.product_name,
+                    active_ingredients = EXCLUDED.active_ingredients,
+                    strength = EXCLUDED.strength,
+                    dosage_form = EXCLUDED.dosage_form,
+                    route_of_administration = EXCLUDED.route_of_administration,
+                    atc_code = EXCLUDED.atc_code,
+                    authorization_holder = EXCLUDED.authorization_holder,
+                    authorization_status = EXCLUDED.authorization_status,
+                    authorization_date = EXCLUDED.authorization_date,
+                    raw_data = EXCLUDED.raw_data,
+                    last_synced_at = NOW()
+                RETURNING (xmax = 0) AS inserted
+                "#
+            )
+            .bind(source.as_str())
+            .bind(&record.source_id)
+            .bind(source.country())
+            .bind(&record.product_name)
+            .bind(&record.active_ingredients)
+            .bind(&record.strength)
+            .bind(&record.dosage_form)
+            .bind(&record.route_of_administration)
+            .bind(&record.atc_code)
+            .bind(&record.authorization_holder)
+            .bind(&record.authorization_status)
+            .bind(record.authorization_date)
+            .bind(&record.raw_data)
+            .fetch_one(&self.pool)
+            .await?;
+
+            if was_inserted {
+                inserted += 1;
+            } else {
+                updated += 1;
+            }
+        }
+
+        Ok((inserted, updated))
+    }
+
+    /// Remove products no longer listed by the source (not seen since the given sync started)
+    pub async fn remove_not_synced_since(&self, source: RegulatorSource, log_id: Uuid) -> Result<i64> {
+        let result = query(
+            r#"
+            DELETE FROM regulator_catalog
+            WHERE source = $1
+              AND last_synced_at < (SELECT sync_started_at FROM catalog_sync_log WHERE id = $2)
+            "#
+        )
+        .bind(source.as_str())
+        .bind(log_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() as i64)
+    }
+
+    pub async fn search(
+        &self,
+        source: RegulatorSource,
+        request: &RegulatorCatalogSearchRequest,
+    ) -> Result<Vec<RegulatorCatalogEntry>> {
+        let limit = request.limit.unwrap_or(20).clamp(1, 100);
+        let offset = request.offset.unwrap_or(0).max(0);
+        let query_text = request.query.as_deref().map(str::trim).filter(|q| !q.is_empty());
+
+        let entries = query_as::<_, RegulatorCatalogEntry>(
+            r#"
+            SELECT id, source, source_id, country, product_name, active_ingredients, strength,
+                   dosage_form, route_of_administration, atc_code, authorization_holder,
+                   authorization_status, authorization_date, raw_data, last_synced_at,
+                   created_at, updated_at
+            FROM regulator_catalog
+            WHERE source = $1
+              AND ($2::TEXT IS NULL
+                   OR search_vector @@ plainto_tsquery('simple', $2)
+                   OR product_name ILIKE '%' || $2 || '%'
+                   OR source_id = $2)
+              AND ($3::TEXT IS NULL OR EXISTS (
+                    SELECT 1 FROM unnest(active_ingredients) AS ingredient
+                    WHERE ingredient ILIKE '%' || $3 || '%'))
+              AND ($4::TEXT IS NULL OR atc_code ILIKE $4 || '%')
+              AND ($5::TEXT IS NULL OR LOWER(authorization_status) = LOWER($5))
+            ORDER BY
+                CASE WHEN $2::TEXT IS NULL THEN 0
+                     ELSE ts_rank(search_vector, plainto_tsquery('simple', $2)) END DESC,
+                product_name
+            LIMIT $6 OFFSET $7
+            "#
+        )
+        .bind(source.as_str())
+        .bind(query_text)
+        .bind(request.ingredient.as_deref().map(str::trim).filter(|i| !i.is_empty()))
+        .bind(request.atc_code.as_deref().map(|a| a.trim().to_uppercase()))
+        .bind(request.authorization_status.as_deref().map(str::trim))
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(entries)
+    }
+
+    pub async fn find_by_source_id(
+        &self,
+        source: RegulatorSource,
+        source_id: &str,
+    ) -> Result<Option<RegulatorCatalogEntry>> {
+        let entry = query_as::<_, RegulatorCatalogEntry>(
+            r#"
+            SELECT id, source, source_id, country, product_name, active_ingredients, strength,
+                   dosage_form, route_of_administration, atc_code, authorization_holder,
+                   authorization_status, authorization_date, raw_data, last_synced_at,
+                   created_at, updated_at
+            FROM regulator_catalog
+            WHERE source = $1 AND source_id = $2
+            "#
+        )
+        .bind(source.as_str())
+        .bind(source_id.trim())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(entry)
+    }
+
+    /// (entry count, most recent sync time)
+    pub async fn get_counts(&self, source: RegulatorSource) -> Result<(i64, Option<DateTime<Utc>>)> {
+        let counts = query_as::<_, (i64, Option<DateTime<Utc>>)>(
+            "SELECT COUNT(*), MAX(last_synced_at) FROM regulator_catalog WHERE source = $1"
+        )
+        .bind(source.as_str())
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(counts)
+    }
+
+    // ============================================================================
+    // Sync Log
+    // ============================================================================
+
+    pub async fn start_sync_log(&self, source: RegulatorSource, sync_type: &str) -> Result<Uuid> {
+        let id: Uuid = query_scalar(
+            r#"
+            INSERT INTO catalog_sync_log (source, sync_type, status, sync_started_at, last_heartbeat_at)
+            VALUES ($1, $2, 'in_progress', NOW(), NOW())
+            RETURNING id
+            "#
+        )
+        .bind(source.as_str())
+        .bind(sync_type)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    pub async fn update_sync_progress(
+        &self,
+        log_id: Uuid,
+        fetched: i32,
+        inserted: i32,
+        updated: i32,
+        skipped: i32,
+    ) -> Result<()> {
+        query(
+            r#"
+            UPDATE catalog_sync_log
+            SET records_fetched = $2, records_inserted = $3, records_updated = $4,
+                records_skipped = $5, last_heartbeat_at = NOW()
+            WHERE id = $1
+            "#
+        )
+        .bind(log_id)
+        .bind(fetched)
+        .bind(inserted)
+        .bind(updated)
+        .bind(skipped)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn complete_sync_log(
+        &self,
+        log_id: Uuid,
+        fetched: i32,
+        inserted: i32,
+        updated: i32,
+        skipped: i32,
+        removed: i32,
+        api_response_time_ms: i32,
+        processing_time_ms: i32,
+    ) -> Result<()> {
+        query(
+            r#"
+            UPDATE catalog_sync_log
+            SET status = 'completed', sync_completed_at = NOW(), last_heartbeat_at = NOW(),
+                records_fetched = $2, records_inserted = $3, records_updated = $4,
+                records_skipped = $5, records_removed = $6,
+                api_response_time_ms = $7, processing_time_ms = $8
+            WHERE id = $1
+            "#
+        )
+        .bind(log_id)
+        .bind(fetched)
+        .bind(inserted)
+        .bind(updated)
+        .bind(skipped)
+        .bind(removed)
+        .bind(api_response_time_ms)
+        .bind(processing_time_ms)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Move a sync back to in_progress for another attempt of its background job.
+    /// Returns false if the sync has finished.
+    pub async fn restart_sync_log(&self, log_id: Uuid) -> Result<bool> {
+        let result = query(
+            r#"
+            UPDATE catalog_sync_log
+            SET status = 'in_progress', error_message = NULL, sync_completed_at = NULL, last_heartbeat_at = NOW()
+            WHERE id = $1 AND status IN ('in_progress', 'failed', 'interrupted')
+            "#
+        )
+        .bind(log_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn fail_sync_log(&self, log_id: Uuid, error_message: &str) -> Result<()> {
+        query(
+            r#"
+            UPDATE catalog_sync_log
+            SET status = 'failed', sync_completed_at = NOW(), error_message = $2
+            WHERE id = $1
+            "#
+        )
+        .bind(log_id)
+        .bind(error_message)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Mark a completed run as suspect. Returns false if the run is not (or no longer) completed.
+    pub async fn mark_sync_suspect(&self, log_id: Uuid, anomalies: &serde_json::Value) -> Result<bool> {
+        let result = query(
+            "UPDATE catalog_sync_log SET status = 'suspect', anomalies = $2 WHERE id = $1 AND status = 'completed'"
+        )
+        .bind(log_id)
+        .bind(anomalies)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Whether a live sync is running for the source (stale runs are ignored)
+    pub async fn is_sync_running(&self, source: RegulatorSource) -> Result<bool> {
+        let running: bool = query_scalar(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM catalog_sync_log
+                WHERE source = $1 AND status = 'in_progress'
+                  AND last_heartbeat_at > NOW() - make_interval(mins => $2)
+            )
+            "#
+        )
+        .bind(source.as_str())
+        .bind(STALE_SYNC_MINUTES)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(running)
+    }
+
+    pub async fn get_sync_logs(
+        &self,
+        source: RegulatorSource,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<CatalogSyncLog>> {
+        let logs = query_as::<_, CatalogSyncLog>(
+            r#"
+            SELECT * FROM catalog_sync_log
+            WHERE source = $1
+            ORDER BY sync_started_at DESC
+            LIMIT $2 OFFSET $3
+            "#
+        )
+        .bind(source.as_str())
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(logs)
+    }
+
+    pub async fn get_last_successful_sync(&self, source: RegulatorSource) -> Result<Option<CatalogSyncLog>> {
+        let log = query_as::<_, CatalogSyncLog>(
+            r#"
+            SELECT * FROM catalog_sync_log
+            WHERE source = $1 AND status = 'completed'
+            ORDER BY sync_completed_at DESC
+            LIMIT 1
+            "#
+        )
+        .bind(source.as_str())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(log)
+    }
+}
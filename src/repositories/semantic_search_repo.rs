@@ -0,0 +1,412 @@
+use pgvector::Vector;
+use sqlx::{query, query_as, query_scalar, PgPool};
+use uuid::Uuid;
+use crate::middleware::error_handling::{AppError, Result};
+use crate::models::semantic_search::{EmbeddingBackfillJob, SearchCandidate, SearchDocument};
+
+const VECTOR_INDEX: &str = "idx_search_embeddings_vector";
+
+/// IVFFlat lists searched per query; more probes trade speed for recall
+const IVFFLAT_PROBES: i32 = 10;
+
+const JOB_COLUMNS: &str = "id, entity_types, full_rebuild, status, triggered_by, records_scanned, \
+    records_embedded, records_removed, error_message, created_by, created_at, started_at, completed_at";
+
+pub struct SemanticSearchRepository {
+    pool: PgPool,
+}
+
+impl SemanticSearchRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    // ========================================================================
+    // Documents
+    // ========================================================================
+
+    /// The next batch of records of a type to embed, in id order after `after`.
+    /// Only available inventory is embedded.
+    pub async fn documents(&self, entity_type: &str, after: Option<Uuid>, limit: i64) -> Result<Vec<SearchDocument>> {
+        let sql = match entity_type {
+            "pharmaceutical" => r#"
+                SELECT p.id AS entity_id,
+                       p.brand_name AS title,
+                       NULLIF(concat_ws(' · ', p.generic_name, p.strength, p.dosage_form, p.manufacturer), '') AS subtitle,
+                       concat_ws('. ', p.brand_name, p.generic_name, p.strength, p.dosage_form, p.manufacturer,
+                                 p.category, p.description, p.storage_requirements) AS content
+                FROM pharmaceuticals p
+                WHERE ($1::UUID IS NULL OR p.id > $1)
+                ORDER BY p.id
+                LIMIT $2
+            "#,
+            "inventory" => r#"
+                SELECT i.id AS entity_id,
+                       p.brand_name AS title,
+                       NULLIF(concat_ws(' · ', p.generic_name, p.strength, 'Batch ' || i.batch_number,
+                                        'Expires ' || i.expiry_date::TEXT), '') AS subtitle,
+                       concat_ws('. ', p.brand_name, p.generic_name, p.strength, p.dosage_form, p.manufacturer,
+                                 p.category, p.description, i.storage_location) AS content
+                FROM inventory i
+                JOIN pharmaceuticals p ON p.id = i.pharmaceutical_id
+                WHERE i.status = 'available' AND ($1::UUID IS NULL OR i.id > $1)
+                ORDER BY i.id
+                LIMIT $2
+            "#,
+            "openfda" => r#"
+                SELECT o.id AS entity_id,
+                       o.brand_name AS title,
+                       NULLIF(concat_ws(' · ', o.generic_name, o.strength, o.dosage_form, o.labeler_name,
+                                        'NDC ' || o.product_ndc), '') AS subtitle,
+                       concat_ws('. ', o.brand_name, o.generic_name, o.strength, o.dosage_form, o.labeler_name,
+                                 array_to_string(o.route, ', '), array_to_string(o.pharm_class, ', '),
+                                 o.product_type) AS content
+                FROM openfda_catalog o
+                WHERE ($1::UUID IS NULL OR o.id > $1)
+                ORDER BY o.id
+                LIMIT $2
+            "#,
+            "ema" => r#"
+                SELECT e.id AS entity_id,
+                       e.product_name AS title,
+                       NULLIF(concat_ws(' · ', e.inn_name, e.strength, e.pharmaceutical_form, e.mah_name,
+                                        e.eu_number), '') AS subtitle,
+                       concat_ws('. ', e.product_name, e.inn_name, e.strength, e.pharmaceutical_form, e.mah_name,
+                                 e.atc_code, e.therapeutic_indication) AS content
+                FROM ema_catalog e
+                WHERE ($1::UUID IS NULL OR e.id > $1)
+                ORDER BY e.id
+                LIMIT $2
+            "#,
+            other => return Err(AppError::BadRequest(format!("Unknown record type '{}'", other))),
+        };
+
+        let documents = query_as::<_, SearchDocument>(sql)
+            .bind(after)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(documents)
+    }
+
+    /// Content hashes of the records already embedded, by record id
+    pub async fn content_hashes(&self, entity_type: &str, entity_ids: &[Uuid]) -> Result<Vec<(Uuid, String)>> {
+        let hashes = query_as::<_, (Uuid, String)>(
+            "SELECT entity_id, content_hash FROM search_embeddings WHERE entity_type = $1 AND entity_id = ANY($2)"
+        )
+        .bind(entity_type)
+        .bind(entity_ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(hashes)
+    }
+
+    pub async fn upsert_embeddings(&self, entity_type: &str, embedded: &[(SearchDocument, String, Vector)]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        for (document, content_hash, embedding) in embedded {
+            query(
+                r#"
+                INSERT INTO search_embeddings (entity_type, entity_id, title, subtitle, content_hash, embedding)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                ON CONFLICT (entity_type, entity_id) DO UPDATE SET
+                    title = EXCLUDED.title,
+                    subtitle = EXCLUDED.subtitle,
+                    content_hash = EXCLUDED.content_hash,
+                    embedding = EXCLUDED.embedding,
+                    embedded_at = NOW()
+                "#
+            )
+            .bind(entity_type)
+            .bind(document.entity_id)
+            .bind(&document.title)
+            .bind(&document.subtitle)
+            .bind(content_hash)
+            .bind(embedding)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Drop embeddings whose record was deleted (or, for inventory, is no longer available)
+    pub async fn remove_orphans(&self, entity_type: &str) -> Result<u64> {
+        let source = match entity_type {
+            "pharmaceutical" => "SELECT 1 FROM pharmaceuticals s WHERE s.id = e.entity_id",
+            "inventory" => "SELECT 1 FROM inventory s WHERE s.id = e.entity_id AND s.status = 'available'",
+            "openfda" => "SELECT 1 FROM openfda_catalog s WHERE s.id = e.entity_id",
+            "ema" => "SELECT 1 FROM ema_catalog s WHERE s.id = e.entity_id",
+            other => return Err(AppError::BadRequest(format!("Unknown record type '{}'", other))),
+        };
+
+        let removed = query(&format!(
+            "DELETE FROM search_embeddings e WHERE e.entity_type = $1 AND NOT EXISTS ({})",
+            source
+        ))
+        .bind(entity_type)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        Ok(removed)
+    }
+
+    // ========================================================================
+    // Search
+    // ========================================================================
+
+    /// Nearest embeddings by cosine similarity; inventory sold since it was embedded is skipped
+    pub async fn vector_candidates(&self, embedding: &Vector, entity_types: &[String], limit: i64) -> Result<Vec<SearchCandidate>> {
+        let mut tx = self.pool.begin().await?;
+
+        query(&format!("SET LOCAL ivfflat.probes = {}", IVFFLAT_PROBES))
+            .execute(&mut *tx)
+            .await?;
+
+        let candidates = query_as::<_, SearchCandidate>(
+            r#"
+            SELECT e.entity_type, e.entity_id, e.title, e.subtitle,
+                   (1 - (e.embedding <=> $1))::FLOAT8 AS score
+            FROM search_embeddings e
+            WHERE e.entity_type = ANY($2)
+              AND (e.entity_type <> 'inventory' OR EXISTS (
+                    SELECT 1 FROM inventory i JOIN users u ON u.id = i.user_id
+                    WHERE i.id = e.entity_id AND i.status = 'available' AND i.taken_down_at IS NULL
+                      AND u.listing_suspended_at IS NULL AND u.suspended_at IS NULL))
+            ORDER BY e.embedding <=> $1
+            LIMIT $3
+            "#
+        )
+        .bind(embedding)
+        .bind(entity_types)
+        .bind(limit)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(candidates)
+    }
+
+    /// Best full-text matches of every requested type, ranked with `ts_rank`
+    pub async fn text_candidates(&self, query_text: &str, entity_types: &[String], limit: i64) -> Result<Vec<SearchCandidate>> {
+        let candidates = query_as::<_, SearchCandidate>(
+            r#"
+            WITH q AS (SELECT plainto_tsquery('english', $1) AS query)
+            (SELECT 'pharmaceutical'::TEXT AS entity_type, p.id AS entity_id, p.brand_name::TEXT AS title,
+                    NULLIF(concat_ws(' · ', p.generic_name, p.strength, p.dosage_form, p.manufacturer), '') AS subtitle,
+                    ts_rank(p.search_vector, q.query)::FLOAT8 AS score
+             FROM pharmaceuticals p, q
+             WHERE 'pharmaceutical' = ANY($2) AND p.search_vector @@ q.query
+             ORDER BY score DESC
+             LIMIT $3)
+            UNION ALL
+            (SELECT 'inventory'::TEXT, i.id, p.brand_name::TEXT,
+                    NULLIF(concat_ws(' · ', p.generic_name, p.strength, 'Batch ' || i.batch_number,
+                                     'Expires ' || i.expiry_date::TEXT), ''),
+                    ts_rank(p.search_vector, q.query)::FLOAT8 AS score
+             FROM inventory i
+             JOIN pharmaceuticals p ON p.id = i.pharmaceutical_id
+             JOIN users u ON u.id = i.user_id, q
+             WHERE 'inventory' = ANY($2) AND i.status = 'available' AND i.taken_down_at IS NULL
+               AND u.listing_suspended_at IS NULL AND u.suspended_at IS NULL
+               AND p.search_vector @@ q.query
+             ORDER BY score DESC
+             LIMIT $3)
+            UNION ALL
+            (SELECT 'openfda'::TEXT, o.id, o.brand_name,
+                    NULLIF(concat_ws(' · ', o.generic_name, o.strength, o.dosage_form, o.labeler_name,
+                                     'NDC ' || o.product_ndc), ''),
+                    ts_rank(o.search_vector, q.query)::FLOAT8 AS score
+             FROM openfda_catalog o, q
+             WHERE 'openfda' = ANY($2) AND o.search_vector @@ q.query
+             ORDER BY score DESC
+             LIMIT $3)
+            UNION ALL
+            (SELECT 'ema'::TEXT, e.id, e.product_name,
+                    NULLIF(concat_ws(' · ', e.inn_name, e.strength, e.pharmaceutical_form, e.mah_name, e.eu_number), ''),
+                    ts_rank(e.search_vector, q.query)::FLOAT8 AS score
+             FROM ema_catalog e, q
+             WHERE 'ema' = ANY($2) AND e.search_vector @@ q.query
+             ORDER BY score DESC
+             LIMIT $3)
+            "#
+        )
+        .bind(query_text)
+        .bind(entity_types)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(candidates)
+    }
+
+    // ========================================================================
+    // Index Maintenance
+    // ========================================================================
+
+    pub async fn embedding_count(&self) -> Result<i64> {
+        let count = query_scalar::<_, i64>("SELECT COUNT(*) FROM search_embeddings")
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(count)
+    }
+
+    /// `lists` the vector index was built with, if it exists
+    pub async fn vector_index_lists(&self) -> Result<Option<i64>> {
+        let lists = query_scalar::<_, Option<i64>>(
+            r#"
+            SELECT split_part(opt, '=', 2)::BIGINT
+            FROM pg_class c, unnest(c.reloptions) AS opt
+            WHERE c.relname = $1 AND opt LIKE 'lists=%'
+            "#
+        )
+        .bind(VECTOR_INDEX)
+        .fetch_optional(&self.pool)
+        .await?
+        .flatten();
+
+        Ok(lists)
+    }
+
+    /// Build a new vector index with `lists` next to the old one, then swap them, so
+    /// searches keep an index throughout
+    pub async fn rebuild_vector_index(&self, lists: i64) -> Result<()> {
+        let staging = format!("{}_rebuild", VECTOR_INDEX);
+
+        query(&format!("DROP INDEX CONCURRENTLY IF EXISTS {}", staging))
+            .execute(&self.pool)
+            .await?;
+        query(&format!(
+            "CREATE INDEX CONCURRENTLY {} ON search_embeddings USING ivfflat (embedding vector_cosine_ops) WITH (lists = {})",
+            staging, lists
+        ))
+        .execute(&self.pool)
+        .await?;
+        query(&format!("DROP INDEX CONCURRENTLY IF EXISTS {}", VECTOR_INDEX))
+            .execute(&self.pool)
+            .await?;
+        query(&format!("ALTER INDEX {} RENAME TO {}", staging, VECTOR_INDEX))
+            .execute(&self.pool)
+            .await?;
+        query("ANALYZE search_embeddings")
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    // ========================================================================
+    // Backfill Jobs
+    // ========================================================================
+
+    /// Queue a job; fails with `Conflict` while another job is pending or running
+    pub async fn create_job(
+        &self,
+        entity_types: &[String],
+        full_rebuild: bool,
+        triggered_by: &str,
+        created_by: Option<Uuid>,
+    ) -> Result<EmbeddingBackfillJob> {
+        let job = query_as::<_, EmbeddingBackfillJob>(&format!(
+            r#"
+            INSERT INTO search_embedding_jobs (entity_types, full_rebuild, triggered_by, created_by)
+            VALUES ($1, $2, $3, $4)
+            RETURNING {}
+            "#,
+            JOB_COLUMNS
+        ))
+        .bind(entity_types)
+        .bind(full_rebuild)
+        .bind(triggered_by)
+        .bind(created_by)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db) if db.code().as_deref() == Some("23505") => AppError::Conflict,
+            other => other.into(),
+        })?;
+
+        Ok(job)
+    }
+
+    pub async fn list_jobs(&self, limit: i64) -> Result<Vec<EmbeddingBackfillJob>> {
+        let jobs = query_as::<_, EmbeddingBackfillJob>(&format!(
+            "SELECT {} FROM search_embedding_jobs ORDER BY created_at DESC LIMIT $1",
+            JOB_COLUMNS
+        ))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(jobs)
+    }
+
+    pub async fn mark_job_running(&self, job_id: Uuid) -> Result<()> {
+        query("UPDATE search_embedding_jobs SET status = 'running', started_at = NOW() WHERE id = $1")
+            .bind(job_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn add_job_progress(&self, job_id: Uuid, scanned: i32, embedded: i32, removed: i32) -> Result<()> {
+        query(
+            r#"
+            UPDATE search_embedding_jobs
+            SET records_scanned = records_scanned + $2,
+                records_embedded = records_embedded + $3,
+                records_removed = records_removed + $4
+            WHERE id = $1
+            "#
+        )
+        .bind(job_id)
+        .bind(scanned)
+        .bind(embedded)
+        .bind(removed)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn finish_job(&self, job_id: Uuid, error_message: Option<&str>) -> Result<()> {
+        query(
+            r#"
+            UPDATE search_embedding_jobs
+            SET status = CASE WHEN $2::TEXT IS NULL THEN 'completed' ELSE 'failed' END,
+                error_message = $2,
+                completed_at = NOW()
+            WHERE id = $1
+            "#
+        )
+        .bind(job_id)
+        .bind(error_message)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Jobs left pending or running by a restart can never finish; fail them so new
+    /// jobs can start
+    pub async fn fail_interrupted_jobs(&self) -> Result<u64> {
+        let failed = query(
+            r#"
+            UPDATE search_embedding_jobs
+            SET status = 'failed', error_message = 'Interrupted by a restart', completed_at = NOW()
+            WHERE status IN ('pending', 'running')
+            "#
+        )
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+
+        Ok(failed)
+    }
+}
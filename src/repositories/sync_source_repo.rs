@@ -0,0 +1,177 @@
+use sqlx::{PgPool, query_as, query_scalar};
+use uuid::Uuid;
+use crate::models::{sync_dashboard::SyncPipelineRow, sync_source::SyncSourceSettings};
+use crate::middleware::error_handling::Result;
+
+pub struct SyncSourceRepository {
+    pool: PgPool,
+}
+
+impl SyncSourceRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn list(&self) -> Result<Vec<SyncSourceSettings>> {
+        let settings = query_as::<_, SyncSourceSettings>(
+            "SELECT * FROM sync_source_settings ORDER BY source"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(settings)
+    }
+
+    pub async fn get(&self, source: &str) -> Result<Option<SyncSourceSettings>> {
+        let settings = query_as::<_, SyncSourceSettings>(
+            "SELECT * FROM sync_source_settings WHERE source = $1"
+        )
+        .bind(source)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(settings)
+    }
+
+    /// Replace the rate limits and API key of a source (creating its row if needed)
+    pub async fn upsert_limits(
+        &self,
+        source: &str,
+        requests_per_minute: Option<i32>,
+        burst_limit: Option<i32>,
+        api_key_encrypted: Option<&str>,
+        updated_by: Uuid,
+    ) -> Result<SyncSourceSettings> {
+        let settings = query_as::<_, SyncSourceSettings>(
+            r#"
+            INSERT INTO sync_source_settings (source, requests_per_minute, burst_limit, api_key_encrypted, updated_by)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (source) DO UPDATE SET
+                requests_per_minute = EXCLUDED.requests_per_minute,
+                burst_limit = EXCLUDED.burst_limit,
+                api_key_encrypted = EXCLUDED.api_key_encrypted,
+                updated_by = EXCLUDED.updated_by,
+                updated_at = NOW()
+            RETURNING *
+            "#
+        )
+        .bind(source)
+        .bind(requests_per_minute)
+        .bind(burst_limit)
+        .bind(api_key_encrypted)
+        .bind(updated_by)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(settings)
+    }
+
+    pub async fn set_paused(
+        &self,
+        source: &str,
+        paused: bool,
+        reason: Option<&str>,
+        user_id: Uuid,
+    ) -> Result<SyncSourceSettings> {
+        let settings = query_as::<_, SyncSourceSettings>(
+            r#"
+            INSERT INTO sync_source_settings (source, scheduler_paused, paused_reason, paused_at, paused_by, updated_by)
+            VALUES ($1, $2, $3, CASE WHEN $2 THEN NOW() END, CASE WHEN $2 THEN $4 END, $4)
+            ON CONFLICT (source) DO UPDATE SET
+                scheduler_paused = EXCLUDED.scheduler_paused,
+                paused_reason = EXCLUDED.paused_reason,
+                paused_at = EXCLUDED.paused_at,
+                paused_by = EXCLUDED.paused_by,
+                updated_by = EXCLUDED.updated_by,
+                updated_at = NOW()
+            RETURNING *
+            "#
+        )
+        .bind(source)
+        .bind(paused)
+        .bind(reason)
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(settings)
+    }
+
+    pub async fn is_scheduler_paused(&self, source: &str) -> Result<bool> {
+        let paused: Option<bool> = query_scalar(
+            "SELECT scheduler_paused FROM sync_source_settings WHERE source = $1"
+        )
+        .bind(source)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(paused.unwrap_or(false))
+    }
+
+    /// Latest run, last success and 7-day run counts of each source, across the OpenFDA,
+    /// EMA and regulator sync logs. Sources without any runs are returned with empty history.
+    pub async fn pipeline_rows(&self, sources: &[String]) -> Result<Vec<SyncPipelineRow>> {
+        let rows = query_as::<_, SyncPipelineRow>(
+            r#"
+            WITH runs AS (
+                SELECT 'openfda'::TEXT AS source, status, sync_started_at, sync_completed_at,
+                       last_heartbeat_at, processing_time_ms, records_fetched, records_inserted,
+                       records_updated, records_failed, error_message
+                FROM openfda_sync_log
+                UNION ALL
+                SELECT 'ema'::TEXT, status, sync_started_at, sync_completed_at,
+                       last_heartbeat_at, processing_time_ms, records_fetched, records_inserted,
+                       records_updated, records_failed, error_message
+                FROM ema_sync_log
+                UNION ALL
+                SELECT source::TEXT, status, sync_started_at, sync_completed_at,
+                       last_heartbeat_at, processing_time_ms, records_fetched, records_inserted,
+                       records_updated, records_failed, error_message
+                FROM catalog_sync_log
+            ),
+            latest AS (
+                SELECT DISTINCT ON (source) *
+                FROM runs
+                ORDER BY source, sync_started_at DESC
+            ),
+            history AS (
+                SELECT
+                    source,
+                    MAX(sync_completed_at) FILTER (WHERE status = 'completed') AS last_success_at,
+                    COUNT(*) FILTER (WHERE sync_started_at > NOW() - INTERVAL '7 days') AS runs_7d,
+                    COUNT(*) FILTER (WHERE status = 'failed' AND sync_started_at > NOW() - INTERVAL '7 days') AS failed_7d,
+                    COUNT(*) FILTER (WHERE status = 'suspect' AND sync_started_at > NOW() - INTERVAL '7 days') AS suspect_7d
+                FROM runs
+                GROUP BY source
+            )
+            SELECT
+                s.source,
+                l.status AS last_status,
+                l.sync_started_at AS last_started_at,
+                l.sync_completed_at AS last_completed_at,
+                l.last_heartbeat_at,
+                l.processing_time_ms AS last_processing_time_ms,
+                l.records_fetched AS last_records_fetched,
+                l.records_inserted AS last_records_inserted,
+                l.records_updated AS last_records_updated,
+                l.records_failed AS last_records_failed,
+                l.error_message AS last_error,
+                h.last_success_at,
+                COALESCE(h.runs_7d, 0) AS runs_7d,
+                COALESCE(h.failed_7d, 0) AS failed_7d,
+                COALESCE(h.suspect_7d, 0) AS suspect_7d,
+                COALESCE(ss.scheduler_paused, FALSE) AS scheduler_paused
+            FROM unnest($1::TEXT[]) WITH ORDINALITY AS s(source, position)
+            LEFT JOIN latest l ON l.source = s.source
+            LEFT JOIN history h ON h.source = s.source
+            LEFT JOIN sync_source_settings ss ON ss.source = s.source
+            ORDER BY s.position
+            "#
+        )
+        .bind(sources)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+}
\ No newline at end of file
@@ -1,10 +1,62 @@
-use sqlx::{PgPool, query, Row};
+use sqlx::{FromRow, PgPool, query, query_as, Row};
 use uuid::Uuid;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use anyhow::anyhow;
-use crate::models::user::{User, CreateUserRequest, UpdateUserRequest};
+use crate::models::user::{User, UserRole, CreateUserRequest, UpdateUserRequest};
 use crate::middleware::error_handling::{Result, AppError};
 use crate::services::encryption_service::EncryptionService;
+use crate::utils::{Decrypt, EncryptedField};
+
+/// Columns every query returning users selects, matching `UserRow`
+const USER_COLUMNS: &str = "id, email, password_hash, company_name, is_verified, role, created_at, updated_at, \
+    email_encrypted, contact_person_encrypted, phone_encrypted, address_encrypted, license_number_encrypted";
+
+/// A `users` row with its PII still encrypted; `decrypt` is the only way to a `User`
+#[derive(FromRow)]
+struct UserRow {
+    id: Uuid,
+    /// Plaintext email of rows written before column encryption
+    email: Option<String>,
+    password_hash: String,
+    company_name: String,
+    is_verified: bool,
+    role: UserRole,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    email_encrypted: Option<EncryptedField>,
+    contact_person_encrypted: Option<EncryptedField>,
+    phone_encrypted: Option<EncryptedField>,
+    address_encrypted: Option<EncryptedField>,
+    license_number_encrypted: Option<EncryptedField>,
+}
+
+impl UserRow {
+    fn decrypt(self, encryption: &EncryptionService) -> Result<User> {
+        // Fall back to the plaintext email when the encrypted one is NULL (migration compatibility)
+        let email = match self.email_encrypted.decrypt(encryption)? {
+            Some(email) => email,
+            None => self.email.ok_or_else(|| AppError::Internal(anyhow!("User {} has no email", self.id)))?,
+        };
+
+        Ok(User {
+            id: self.id,
+            email,
+            password_hash: self.password_hash,
+            company_name: self.company_name,
+            contact_person: self
+                .contact_person_encrypted
+                .decrypt(encryption)?
+                .unwrap_or_else(|| "Unknown".to_string()),
+            phone: self.phone_encrypted.decrypt(encryption)?,
+            address: self.address_encrypted.decrypt(encryption)?,
+            license_number: self.license_number_encrypted.decrypt(encryption)?,
+            is_verified: self.is_verified,
+            role: self.role,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        })
+    }
+}
 
 pub struct UserRepository {
     pool: PgPool,
@@ -25,34 +77,23 @@ impl UserRepository {
     pub async fn create(&self, request: &CreateUserRequest, password_hash: &str) -> Result<User> {
         // 🔒 PRODUCTION ENCRYPTION: Hash for lookup + Encrypt for storage
         let email_hash = EncryptionService::hash_for_lookup(&request.email);
-        let email_encrypted = self.encryption.encrypt(&request.email)
-            .map_err(|e| AppError::Internal(anyhow!("Encryption failed: {:?}", e)))?;
-        let contact_person_encrypted = self.encryption.encrypt(&request.contact_person)
-            .map_err(|e| AppError::Internal(anyhow!("Encryption failed: {:?}", e)))?;
-        let phone_encrypted = match &request.phone {
-            Some(s) => Some(self.encryption.encrypt(s).map_err(|e| AppError::Internal(anyhow!("Encryption failed: {:?}", e)))?),
-            None => None,
-        };
-        let address_encrypted = match &request.address {
-            Some(s) => Some(self.encryption.encrypt(s).map_err(|e| AppError::Internal(anyhow!("Encryption failed: {:?}", e)))?),
-            None => None,
-        };
-        let license_number_encrypted = match &request.license_number {
-            Some(s) => Some(self.encryption.encrypt(s).map_err(|e| AppError::Internal(anyhow!("Encryption failed: {:?}", e)))?),
-            None => None,
-        };
+        let email_encrypted = EncryptedField::encrypt(&self.encryption, &request.email)?;
+        let contact_person_encrypted = EncryptedField::encrypt(&self.encryption, &request.contact_person)?;
+        let phone_encrypted = EncryptedField::encrypt_optional(&self.encryption, request.phone.as_ref())?;
+        let address_encrypted = EncryptedField::encrypt_optional(&self.encryption, request.address.as_ref())?;
+        let license_number_encrypted = EncryptedField::encrypt_optional(&self.encryption, request.license_number.as_ref())?;
 
-        let row = query(
+        let row = query_as::<_, UserRow>(&format!(
             r#"
             INSERT INTO users (
                 email, password_hash, company_name, contact_person, phone, address, license_number,
                 email_hash, email_encrypted, contact_person_encrypted, phone_encrypted, address_encrypted, license_number_encrypted
             )
             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
-            RETURNING id, password_hash, company_name, is_verified, role, created_at, updated_at,
-                      email_encrypted, contact_person_encrypted, phone_encrypted, address_encrypted, license_number_encrypted
-            "#
-        )
+            RETURNING {}
+            "#,
+            USER_COLUMNS
+        ))
         .bind(&request.email)  // Temporary: for backwards compat during migration
         .bind(password_hash)
         .bind(&request.company_name)
@@ -70,229 +111,39 @@ impl UserRepository {
         .await?;
 
         // 🔒 DECRYPT on read - application-layer only
-        let email = self.encryption.decrypt(&row.try_get::<String, _>("email_encrypted")?)
-            .map_err(|e| AppError::Internal(anyhow!("Decryption failed: {:?}", e)))?;
-        let contact_person = self.encryption.decrypt(&row.try_get::<String, _>("contact_person_encrypted")?)
-            .map_err(|e| AppError::Internal(anyhow!("Decryption failed: {:?}", e)))?;
-        let phone: Option<String> = row.try_get("phone_encrypted")?;
-        let phone = match phone {
-            Some(encrypted) => Some(self.encryption.decrypt(&encrypted)
-                .map_err(|e| AppError::Internal(anyhow!("Decryption failed: {:?}", e)))?),
-            None => None,
-        };
-        let address: Option<String> = row.try_get("address_encrypted")?;
-        let address = match address {
-            Some(encrypted) => Some(self.encryption.decrypt(&encrypted)
-                .map_err(|e| AppError::Internal(anyhow!("Decryption failed: {:?}", e)))?),
-            None => None,
-        };
-        let license_number: Option<String> = row.try_get("license_number_encrypted")?;
-        let license_number = match license_number {
-            Some(encrypted) => Some(self.encryption.decrypt(&encrypted)
-                .map_err(|e| AppError::Internal(anyhow!("Decryption failed: {:?}", e)))?),
-            None => None,
-        };
-
-        Ok(User {
-            id: row.try_get("id")?,
-            email,
-            password_hash: row.try_get("password_hash")?,
-            company_name: row.try_get("company_name")?,
-            contact_person,
-            phone,
-            address,
-            license_number,
-            is_verified: row.try_get("is_verified")?,
-            role: row.try_get("role")?,
-            created_at: row.try_get("created_at")?,
-            updated_at: row.try_get("updated_at")?,
-        })
+        row.decrypt(&self.encryption)
     }
 
     pub async fn find_by_email(&self, email: &str) -> Result<Option<User>> {
         // 🔒 PRODUCTION: Query by hash, decrypt on read
         let email_hash = EncryptionService::hash_for_lookup(email);
 
-        let row = query(
-            r#"
-            SELECT id, email, email_hash, password_hash, company_name, is_verified, role, created_at, updated_at,
-                   email_encrypted, contact_person_encrypted, phone_encrypted, address_encrypted, license_number_encrypted
-            FROM users
-            WHERE email_hash = $1
-            "#
-        )
-        .bind(&email_hash)
-        .fetch_optional(&self.pool)
-        .await?;
+        let row = query_as::<_, UserRow>(&format!("SELECT {} FROM users WHERE email_hash = $1", USER_COLUMNS))
+            .bind(&email_hash)
+            .fetch_optional(&self.pool)
+            .await?;
 
-        match row {
-            Some(row) => {
-                // Decrypt PII on read (handle NULL for migration compatibility)
-                let email = if let Ok(email_encrypted) = row.try_get::<Option<String>, _>("email_encrypted") {
-                    if let Some(encrypted) = email_encrypted {
-                        self.encryption.decrypt(&encrypted)
-                            .map_err(|e| AppError::Internal(anyhow!("Decryption failed: {:?}", e)))?
-                    } else {
-                        // Fallback to plaintext email if encrypted field is NULL (migration compatibility)
-                        row.try_get("email")?
-                    }
-                } else {
-                    row.try_get("email")?  // Fallback to plaintext
-                };
-
-                let contact_person = if let Ok(contact_person_encrypted) = row.try_get::<Option<String>, _>("contact_person_encrypted") {
-                    if let Some(encrypted) = contact_person_encrypted {
-                        Some(self.encryption.decrypt(&encrypted)
-                            .map_err(|e| AppError::Internal(anyhow!("Decryption failed: {:?}", e)))?)
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                };
-
-                let phone = if let Ok(phone_encrypted) = row.try_get::<Option<String>, _>("phone_encrypted") {
-                    if let Some(encrypted) = phone_encrypted {
-                        Some(self.encryption.decrypt(&encrypted)
-                            .map_err(|e| AppError::Internal(anyhow!("Decryption failed: {:?}", e)))?)
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                };
-
-                let address = if let Ok(address_encrypted) = row.try_get::<Option<String>, _>("address_encrypted") {
-                    if let Some(encrypted) = address_encrypted {
-                        Some(self.encryption.decrypt(&encrypted)
-                            .map_err(|e| AppError::Internal(anyhow!("Decryption failed: {:?}", e)))?)
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                };
-
-                let license_number = if let Ok(license_number_encrypted) = row.try_get::<Option<String>, _>("license_number_encrypted") {
-                    if let Some(encrypted) = license_number_encrypted {
-                        Some(self.encryption.decrypt(&encrypted)
-                            .map_err(|e| AppError::Internal(anyhow!("Decryption failed: {:?}", e)))?)
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                };
-
-                Ok(Some(User {
-                    id: row.try_get("id")?,
-                    email,
-                    password_hash: row.try_get("password_hash")?,
-                    company_name: row.try_get("company_name")?,
-                    contact_person: contact_person.unwrap_or_else(|| "Unknown".to_string()),
-                    phone,
-                    address,
-                    license_number,
-                    is_verified: row.try_get("is_verified")?,
-                    role: row.try_get("role")?,
-                    created_at: row.try_get("created_at")?,
-                    updated_at: row.try_get("updated_at")?,
-                }))
-            },
-            None => Ok(None),
-        }
+        row.map(|row| row.decrypt(&self.encryption)).transpose()
     }
 
     pub async fn find_by_id(&self, id: Uuid) -> Result<Option<User>> {
         // 🔒 PRODUCTION: Query encrypted columns, decrypt on read
-        let row = query(
-            r#"
-            SELECT id, email, email_hash, password_hash, company_name, is_verified, role, created_at, updated_at,
-                   email_encrypted, contact_person_encrypted, phone_encrypted, address_encrypted, license_number_encrypted
-            FROM users
-            WHERE id = $1
-            "#
-        )
-        .bind(id)
-        .fetch_optional(&self.pool)
-        .await?;
+        let row = query_as::<_, UserRow>(&format!("SELECT {} FROM users WHERE id = $1", USER_COLUMNS))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
 
-        match row {
-            Some(row) => {
-                // Decrypt PII on read (handle NULL for migration compatibility)
-                let email = if let Ok(email_encrypted) = row.try_get::<Option<String>, _>("email_encrypted") {
-                    if let Some(encrypted) = email_encrypted {
-                        self.encryption.decrypt(&encrypted)
-                            .map_err(|e| AppError::Internal(anyhow!("Decryption failed: {:?}", e)))?
-                    } else {
-                        // Fallback to plaintext email if encrypted field is NULL (migration compatibility)
-                        row.try_get("email")?
-                    }
-                } else {
-                    row.try_get("email")?  // Fallback to plaintext
-                };
-
-                let contact_person = if let Ok(contact_person_encrypted) = row.try_get::<Option<String>, _>("contact_person_encrypted") {
-                    if let Some(encrypted) = contact_person_encrypted {
-                        Some(self.encryption.decrypt(&encrypted)
-                            .map_err(|e| AppError::Internal(anyhow!("Decryption failed: {:?}", e)))?)
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                };
-
-                let phone = if let Ok(phone_encrypted) = row.try_get::<Option<String>, _>("phone_encrypted") {
-                    if let Some(encrypted) = phone_encrypted {
-                        Some(self.encryption.decrypt(&encrypted)
-                            .map_err(|e| AppError::Internal(anyhow!("Decryption failed: {:?}", e)))?)
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                };
-
-                let address = if let Ok(address_encrypted) = row.try_get::<Option<String>, _>("address_encrypted") {
-                    if let Some(encrypted) = address_encrypted {
-                        Some(self.encryption.decrypt(&encrypted)
-                            .map_err(|e| AppError::Internal(anyhow!("Decryption failed: {:?}", e)))?)
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                };
-
-                let license_number = if let Ok(license_number_encrypted) = row.try_get::<Option<String>, _>("license_number_encrypted") {
-                    if let Some(encrypted) = license_number_encrypted {
-                        Some(self.encryption.decrypt(&encrypted)
-                            .map_err(|e| AppError::Internal(anyhow!("Decryption failed: {:?}", e)))?)
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                };
-
-                Ok(Some(User {
-                    id: row.try_get("id")?,
-                    email,
-                    password_hash: row.try_get("password_hash")?,
-                    company_name: row.try_get("company_name")?,
-                    contact_person: contact_person.unwrap_or_else(|| "Unknown".to_string()),
-                    phone,
-                    address,
-                    license_number,
-                    is_verified: row.try_get("is_verified")?,
-                    role: row.try_get("role")?,
-                    created_at: row.try_get("created_at")?,
-                    updated_at: row.try_get("updated_at")?,
-                }))
-            },
-            None => Ok(None),
-        }
+        row.map(|row| row.decrypt(&self.encryption)).transpose()
+    }
+
+    /// Users among the given ids; missing ids are skipped. Order is unspecified.
+    pub async fn find_by_ids(&self, ids: &[Uuid]) -> Result<Vec<User>> {
+        let rows = query_as::<_, UserRow>(&format!("SELECT {} FROM users WHERE id = ANY($1)", USER_COLUMNS))
+            .bind(ids)
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.into_iter().map(|row| row.decrypt(&self.encryption)).collect()
     }
 
     pub async fn update(&self, user_id: Uuid, request: &UpdateUserRequest) -> Result<User> {
@@ -320,7 +171,7 @@ impl UserRepository {
 
         // Update contact_person if provided (with encryption)
         if let Some(ref contact_person) = request.contact_person {
-            let encrypted = self.encryption.encrypt(contact_person)?;
+            let encrypted = EncryptedField::encrypt(&self.encryption, contact_person)?;
             query("UPDATE users SET contact_person_encrypted = $1, updated_at = $2 WHERE id = $3")
                 .bind(&encrypted)
                 .bind(now)
@@ -331,7 +182,7 @@ impl UserRepository {
 
         // Update phone if provided (with encryption)
         if let Some(ref phone) = request.phone {
-            let encrypted = self.encryption.encrypt(phone)?;
+            let encrypted = EncryptedField::encrypt(&self.encryption, phone)?;
             query("UPDATE users SET phone_encrypted = $1, updated_at = $2 WHERE id = $3")
                 .bind(&encrypted)
                 .bind(now)
@@ -342,7 +193,7 @@ impl UserRepository {
 
         // Update address if provided (with encryption)
         if let Some(ref address) = request.address {
-            let encrypted = self.encryption.encrypt(address)?;
+            let encrypted = EncryptedField::encrypt(&self.encryption, address)?;
             query("UPDATE users SET address_encrypted = $1, updated_at = $2 WHERE id = $3")
                 .bind(&encrypted)
                 .bind(now)
@@ -353,7 +204,7 @@ impl UserRepository {
 
         // Update license_number if provided (with encryption)
         if let Some(ref license_number) = request.license_number {
-            let encrypted = self.encryption.encrypt(license_number)?;
+            let encrypted = EncryptedField::encrypt(&self.encryption, license_number)?;
             query("UPDATE users SET license_number_encrypted = $1, updated_at = $2 WHERE id = $3")
                 .bind(&encrypted)
                 .bind(now)
@@ -362,6 +213,16 @@ impl UserRepository {
                 .await?;
         }
 
+        // Update country_code if provided (announcement targeting; not sensitive)
+        if let Some(ref country_code) = request.country_code {
+            query("UPDATE users SET country_code = $1, updated_at = $2 WHERE id = $3")
+                .bind(country_code.to_uppercase())
+                .bind(now)
+                .bind(user_id)
+                .execute(&self.pool)
+                .await?;
+        }
+
         // Fetch and return updated user
         self.find_by_id(user_id)
             .await?
@@ -408,14 +269,7 @@ impl UserRepository {
         let offset = offset.unwrap_or(0);
 
         // Query encrypted columns
-        let mut query_str = r#"
-            SELECT id, email, email_hash, password_hash, company_name, is_verified, role,
-                   created_at, updated_at,
-                   email_encrypted, contact_person_encrypted, phone_encrypted,
-                   address_encrypted, license_number_encrypted
-            FROM users
-            WHERE 1=1
-        "#.to_string();
+        let mut query_str = format!("SELECT {} FROM users WHERE 1=1", USER_COLUMNS);
 
         let mut param_count = 1;
 
@@ -437,7 +291,7 @@ impl UserRepository {
         query_str.push_str(" ORDER BY created_at DESC");
         query_str.push_str(&format!(" LIMIT ${} OFFSET ${}", param_count, param_count + 1));
 
-        let mut query_builder = query(&query_str);
+        let mut query_builder = query_as::<_, UserRow>(&query_str);
 
         if let Some(role) = role_filter {
             query_builder = query_builder.bind(role);
@@ -455,86 +309,8 @@ impl UserRepository {
             .fetch_all(&self.pool)
             .await?;
 
-        // Decrypt PII for each user (production-safe error handling)
-        let mut users = Vec::new();
-        for row in rows {
-            // Decrypt email
-            let email = if let Ok(email_encrypted) = row.try_get::<Option<String>, _>("email_encrypted") {
-                if let Some(encrypted) = email_encrypted {
-                    self.encryption.decrypt(&encrypted)
-                        .map_err(|e| AppError::Internal(anyhow!("Decryption failed: {:?}", e)))?
-                } else {
-                    row.try_get("email")?
-                }
-            } else {
-                row.try_get("email")?
-            };
-
-            // Decrypt contact_person
-            let contact_person = if let Ok(contact_person_encrypted) = row.try_get::<Option<String>, _>("contact_person_encrypted") {
-                if let Some(encrypted) = contact_person_encrypted {
-                    Some(self.encryption.decrypt(&encrypted)
-                        .map_err(|e| AppError::Internal(anyhow!("Decryption failed: {:?}", e)))?)
-                } else {
-                    None
-                }
-            } else {
-                None
-            };
-
-            // Decrypt phone
-            let phone = if let Ok(phone_encrypted) = row.try_get::<Option<String>, _>("phone_encrypted") {
-                if let Some(encrypted) = phone_encrypted {
-                    Some(self.encryption.decrypt(&encrypted)
-                        .map_err(|e| AppError::Internal(anyhow!("Decryption failed: {:?}", e)))?)
-                } else {
-                    None
-                }
-            } else {
-                None
-            };
-
-            // Decrypt address
-            let address = if let Ok(address_encrypted) = row.try_get::<Option<String>, _>("address_encrypted") {
-                if let Some(encrypted) = address_encrypted {
-                    Some(self.encryption.decrypt(&encrypted)
-                        .map_err(|e| AppError::Internal(anyhow!("Decryption failed: {:?}", e)))?)
-                } else {
-                    None
-                }
-            } else {
-                None
-            };
-
-            // Decrypt license_number
-            let license_number = if let Ok(license_number_encrypted) = row.try_get::<Option<String>, _>("license_number_encrypted") {
-                if let Some(encrypted) = license_number_encrypted {
-                    Some(self.encryption.decrypt(&encrypted)
-                        .map_err(|e| AppError::Internal(anyhow!("Decryption failed: {:?}", e)))?)
-                } else {
-                    None
-                }
-            } else {
-                None
-            };
-
-            users.push(User {
-                id: row.try_get("id")?,
-                email,
-                password_hash: row.try_get("password_hash")?,
-                company_name: row.try_get("company_name")?,
-                contact_person: contact_person.unwrap_or_else(|| "Unknown".to_string()),
-                phone,
-                address,
-                license_number,
-                is_verified: row.try_get("is_verified")?,
-                role: row.try_get("role")?,
-                created_at: row.try_get("created_at")?,
-                updated_at: row.try_get("updated_at")?,
-            });
-        }
-
-        Ok(users)
+        // Decrypt PII for each user
+        rows.into_iter().map(|row| row.decrypt(&self.encryption)).collect()
     }
 
     /// Count total users with optional filters
@@ -570,93 +346,22 @@ impl UserRepository {
 
     /// 🔒 PRODUCTION: Set user verification status (admin only) - with PII decryption
     pub async fn set_verified(&self, user_id: Uuid, verified: bool) -> Result<User> {
-        let row = query(
+        let row = query_as::<_, UserRow>(&format!(
             r#"
             UPDATE users
             SET is_verified = $1, updated_at = $2
             WHERE id = $3
-            RETURNING id, email, email_hash, password_hash, company_name, is_verified, role,
-                      created_at, updated_at,
-                      email_encrypted, contact_person_encrypted, phone_encrypted,
-                      address_encrypted, license_number_encrypted
-            "#
-        )
+            RETURNING {}
+            "#,
+            USER_COLUMNS
+        ))
         .bind(verified)
         .bind(Utc::now())
         .bind(user_id)
         .fetch_one(&self.pool)
         .await?;
 
-        // Decrypt PII
-        let email = if let Ok(email_encrypted) = row.try_get::<Option<String>, _>("email_encrypted") {
-            if let Some(encrypted) = email_encrypted {
-                self.encryption.decrypt(&encrypted)
-                    .map_err(|e| AppError::Internal(anyhow!("Decryption failed: {:?}", e)))?
-            } else {
-                row.try_get("email")?
-            }
-        } else {
-            row.try_get("email")?
-        };
-
-        let contact_person = if let Ok(contact_person_encrypted) = row.try_get::<Option<String>, _>("contact_person_encrypted") {
-            if let Some(encrypted) = contact_person_encrypted {
-                Some(self.encryption.decrypt(&encrypted)
-                    .map_err(|e| AppError::Internal(anyhow!("Decryption failed: {:?}", e)))?)
-            } else {
-                None
-            }
-        } else {
-            None
-        };
-
-        let phone = if let Ok(phone_encrypted) = row.try_get::<Option<String>, _>("phone_encrypted") {
-            if let Some(encrypted) = phone_encrypted {
-                Some(self.encryption.decrypt(&encrypted)
-                    .map_err(|e| AppError::Internal(anyhow!("Decryption failed: {:?}", e)))?)
-            } else {
-                None
-            }
-        } else {
-            None
-        };
-
-        let address = if let Ok(address_encrypted) = row.try_get::<Option<String>, _>("address_encrypted") {
-            if let Some(encrypted) = address_encrypted {
-                Some(self.encryption.decrypt(&encrypted)
-                    .map_err(|e| AppError::Internal(anyhow!("Decryption failed: {:?}", e)))?)
-            } else {
-                None
-            }
-        } else {
-            None
-        };
-
-        let license_number = if let Ok(license_number_encrypted) = row.try_get::<Option<String>, _>("license_number_encrypted") {
-            if let Some(encrypted) = license_number_encrypted {
-                Some(self.encryption.decrypt(&encrypted)
-                    .map_err(|e| AppError::Internal(anyhow!("Decryption failed: {:?}", e)))?)
-            } else {
-                None
-            }
-        } else {
-            None
-        };
-
-        Ok(User {
-            id: row.try_get("id")?,
-            email,
-            password_hash: row.try_get("password_hash")?,
-            company_name: row.try_get("company_name")?,
-            contact_person: contact_person.unwrap_or_else(|| "Unknown".to_string()),
-            phone,
-            address,
-            license_number,
-            is_verified: row.try_get("is_verified")?,
-            role: row.try_get("role")?,
-            created_at: row.try_get("created_at")?,
-            updated_at: row.try_get("updated_at")?,
-        })
+        row.decrypt(&self.encryption)
     }
 
     /// 🔒 PRODUCTION: Set user role (superadmin only) - with PII decryption
@@ -666,17 +371,15 @@ impl UserRepository {
         role: crate::models::user::UserRole,
         changed_by: Uuid,
     ) -> Result<User> {
-        let row = query(
+        let row = query_as::<_, UserRow>(&format!(
             r#"
             UPDATE users
             SET role = $1, role_changed_at = $2, role_changed_by = $3, updated_at = $4
             WHERE id = $5
-            RETURNING id, email, email_hash, password_hash, company_name, is_verified, role,
-                      created_at, updated_at,
-                      email_encrypted, contact_person_encrypted, phone_encrypted,
-                      address_encrypted, license_number_encrypted
-            "#
-        )
+            RETURNING {}
+            "#,
+            USER_COLUMNS
+        ))
         .bind(role)
         .bind(Utc::now())
         .bind(changed_by)
@@ -685,173 +388,24 @@ impl UserRepository {
         .fetch_one(&self.pool)
         .await?;
 
-        // Decrypt PII
-        let email = if let Ok(email_encrypted) = row.try_get::<Option<String>, _>("email_encrypted") {
-            if let Some(encrypted) = email_encrypted {
-                self.encryption.decrypt(&encrypted)
-                    .map_err(|e| AppError::Internal(anyhow!("Decryption failed: {:?}", e)))?
-            } else {
-                row.try_get("email")?
-            }
-        } else {
-            row.try_get("email")?
-        };
-
-        let contact_person = if let Ok(contact_person_encrypted) = row.try_get::<Option<String>, _>("contact_person_encrypted") {
-            if let Some(encrypted) = contact_person_encrypted {
-                Some(self.encryption.decrypt(&encrypted)
-                    .map_err(|e| AppError::Internal(anyhow!("Decryption failed: {:?}", e)))?)
-            } else {
-                None
-            }
-        } else {
-            None
-        };
-
-        let phone = if let Ok(phone_encrypted) = row.try_get::<Option<String>, _>("phone_encrypted") {
-            if let Some(encrypted) = phone_encrypted {
-                Some(self.encryption.decrypt(&encrypted)
-                    .map_err(|e| AppError::Internal(anyhow!("Decryption failed: {:?}", e)))?)
-            } else {
-                None
-            }
-        } else {
-            None
-        };
-
-        let address = if let Ok(address_encrypted) = row.try_get::<Option<String>, _>("address_encrypted") {
-            if let Some(encrypted) = address_encrypted {
-                Some(self.encryption.decrypt(&encrypted)
-                    .map_err(|e| AppError::Internal(anyhow!("Decryption failed: {:?}", e)))?)
-            } else {
-                None
-            }
-        } else {
-            None
-        };
-
-        let license_number = if let Ok(license_number_encrypted) = row.try_get::<Option<String>, _>("license_number_encrypted") {
-            if let Some(encrypted) = license_number_encrypted {
-                Some(self.encryption.decrypt(&encrypted)
-                    .map_err(|e| AppError::Internal(anyhow!("Decryption failed: {:?}", e)))?)
-            } else {
-                None
-            }
-        } else {
-            None
-        };
-
-        Ok(User {
-            id: row.try_get("id")?,
-            email,
-            password_hash: row.try_get("password_hash")?,
-            company_name: row.try_get("company_name")?,
-            contact_person: contact_person.unwrap_or_else(|| "Unknown".to_string()),
-            phone,
-            address,
-            license_number,
-            is_verified: row.try_get("is_verified")?,
-            role: row.try_get("role")?,
-            created_at: row.try_get("created_at")?,
-            updated_at: row.try_get("updated_at")?,
-        })
+        row.decrypt(&self.encryption)
     }
 
-    /// 🔒 PRODUCTION: Get pending verification queue (unverified users only) - with PII decryption
+    /// 🔒 PRODUCTION: Get pending verification queue (unverified, not rejected users) - with PII decryption
     pub async fn get_verification_queue(&self) -> Result<Vec<User>> {
-        let rows = query(
+        let rows = query_as::<_, UserRow>(&format!(
             r#"
-            SELECT id, email, email_hash, password_hash, company_name, is_verified, role,
-                   created_at, updated_at,
-                   email_encrypted, contact_person_encrypted, phone_encrypted,
-                   address_encrypted, license_number_encrypted
+            SELECT {}
             FROM users
-            WHERE is_verified = false AND role = 'user'
+            WHERE is_verified = false AND role = 'user' AND verification_rejected_at IS NULL
             ORDER BY created_at ASC
-            "#
-        )
+            "#,
+            USER_COLUMNS
+        ))
         .fetch_all(&self.pool)
         .await?;
 
-        // Decrypt PII for each user (production-safe error handling)
-        let mut users = Vec::new();
-        for row in rows {
-            // Decrypt email
-            let email = if let Ok(email_encrypted) = row.try_get::<Option<String>, _>("email_encrypted") {
-                if let Some(encrypted) = email_encrypted {
-                    self.encryption.decrypt(&encrypted)
-                        .map_err(|e| AppError::Internal(anyhow!("Decryption failed: {:?}", e)))?
-                } else {
-                    row.try_get("email")?
-                }
-            } else {
-                row.try_get("email")?
-            };
-
-            // Decrypt contact_person
-            let contact_person = if let Ok(contact_person_encrypted) = row.try_get::<Option<String>, _>("contact_person_encrypted") {
-                if let Some(encrypted) = contact_person_encrypted {
-                    Some(self.encryption.decrypt(&encrypted)
-                        .map_err(|e| AppError::Internal(anyhow!("Decryption failed: {:?}", e)))?)
-                } else {
-                    None
-                }
-            } else {
-                None
-            };
-
-            // Decrypt phone
-            let phone = if let Ok(phone_encrypted) = row.try_get::<Option<String>, _>("phone_encrypted") {
-                if let Some(encrypted) = phone_encrypted {
-                    Some(self.encryption.decrypt(&encrypted)
-                        .map_err(|e| AppError::Internal(anyhow!("Decryption failed: {:?}", e)))?)
-                } else {
-                    None
-                }
-            } else {
-                None
-            };
-
-            // Decrypt address
-            let address = if let Ok(address_encrypted) = row.try_get::<Option<String>, _>("address_encrypted") {
-                if let Some(encrypted) = address_encrypted {
-                    Some(self.encryption.decrypt(&encrypted)
-                        .map_err(|e| AppError::Internal(anyhow!("Decryption failed: {:?}", e)))?)
-                } else {
-                    None
-                }
-            } else {
-                None
-            };
-
-            // Decrypt license_number
-            let license_number = if let Ok(license_number_encrypted) = row.try_get::<Option<String>, _>("license_number_encrypted") {
-                if let Some(encrypted) = license_number_encrypted {
-                    Some(self.encryption.decrypt(&encrypted)
-                        .map_err(|e| AppError::Internal(anyhow!("Decryption failed: {:?}", e)))?)
-                } else {
-                    None
-                }
-            } else {
-                None
-            };
-
-            users.push(User {
-                id: row.try_get("id")?,
-                email,
-                password_hash: row.try_get("password_hash")?,
-                company_name: row.try_get("company_name")?,
-                contact_person: contact_person.unwrap_or_else(|| "Unknown".to_string()),
-                phone,
-                address,
-                license_number,
-                is_verified: row.try_get("is_verified")?,
-                role: row.try_get("role")?,
-                created_at: row.try_get("created_at")?,
-                updated_at: row.try_get("updated_at")?,
-            });
-        }
-
-        Ok(users)
+        // Decrypt PII for each user
+        rows.into_iter().map(|row| row.decrypt(&self.encryption)).collect()
     }
-}
\ No newline at end of file
+}
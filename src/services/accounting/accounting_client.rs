@@ -0,0 +1,223 @@
+// Accounting client layer
+// Types shared by the QuickBooks Online and Xero clients and a client that dispatches to
+// the connection's provider: OAuth2 authorization code flow with refresh tokens, and
+// invoice / payment creation with provider idempotency keys.
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use std::time::Duration as StdDuration;
+use thiserror::Error;
+
+use super::quickbooks_client::QuickBooksClient;
+use super::xero_client::XeroClient;
+use crate::models::accounting::AccountingProvider;
+
+pub(super) const REQUEST_TIMEOUT: StdDuration = StdDuration::from_secs(30);
+
+#[derive(Error, Debug)]
+pub enum AccountingClientError {
+    #[error("{0} API error ({1}): {2}")]
+    Api(&'static str, reqwest::StatusCode, String),
+
+    #[error("Authorization failed: {0}")]
+    Auth(String),
+
+    #[error("Network error: {0}")]
+    Network(#[from] reqwest::Error),
+
+    #[error("Unexpected response: {0}")]
+    InvalidResponse(String),
+
+    #[error("Invalid configuration: {0}")]
+    Config(String),
+}
+
+pub type Result<T> = std::result::Result<T, AccountingClientError>;
+
+// ============================================================================
+// OAuth2
+// ============================================================================
+
+/// OAuth2 app registered by the user with the provider
+#[derive(Debug, Clone)]
+pub struct OAuthApp {
+    pub client_id: String,
+    pub client_secret: String,
+    pub redirect_uri: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct OAuthTokens {
+    pub access_token: String,
+    /// Both providers rotate the refresh token on every refresh
+    pub refresh_token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    refresh_token: String,
+    expires_in: i64,
+}
+
+/// Call a token endpoint (code exchange or refresh) with HTTP basic client authentication
+pub(super) async fn request_tokens(
+    http: &reqwest::Client,
+    provider: &'static str,
+    token_url: &str,
+    app: &OAuthApp,
+    form: &[(&str, &str)],
+) -> Result<OAuthTokens> {
+    let response = http
+        .post(token_url)
+        .basic_auth(&app.client_id, Some(&app.client_secret))
+        .header("Accept", "application/json")
+        .form(form)
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(AccountingClientError::Auth(format!("{} token request failed ({}): {}", provider, status, body)));
+    }
+
+    let tokens: TokenResponse = response
+        .json()
+        .await
+        .map_err(|e| AccountingClientError::InvalidResponse(e.to_string()))?;
+
+    Ok(OAuthTokens {
+        access_token: tokens.access_token,
+        refresh_token: tokens.refresh_token,
+        expires_at: Utc::now() + Duration::seconds(tokens.expires_in),
+    })
+}
+
+/// Fail with the provider's error body on a non-2xx response
+pub(super) async fn check_status(provider: &'static str, response: reqwest::Response) -> Result<reqwest::Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    let body = response.text().await.unwrap_or_default();
+    if status == reqwest::StatusCode::UNAUTHORIZED {
+        return Err(AccountingClientError::Auth(format!("{} rejected the access token: {}", provider, body)));
+    }
+    Err(AccountingClientError::Api(provider, status, body))
+}
+
+// ============================================================================
+// Documents
+// ============================================================================
+
+/// Single-line invoice for a marketplace sale
+#[derive(Debug, Clone)]
+pub struct InvoiceDraft {
+    /// Atlas reference shown on the invoice (QuickBooks DocNumber / Xero Reference)
+    pub reference: String,
+    pub date: NaiveDate,
+    /// QuickBooks customer ID / Xero contact ID
+    pub customer_ref: String,
+    /// QuickBooks item ID / Xero sales account code
+    pub item_ref: String,
+    pub description: String,
+    pub quantity: i32,
+    pub unit_price: Decimal,
+    pub total: Decimal,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreatedInvoice {
+    pub id: String,
+    pub number: Option<String>,
+}
+
+// ============================================================================
+// Client
+// ============================================================================
+
+/// Client of the connection's provider
+pub enum AccountingClient {
+    QuickBooks(QuickBooksClient),
+    Xero(XeroClient),
+}
+
+impl AccountingClient {
+    /// `tenant_id` is the QuickBooks realm ID / Xero tenant ID once authorized
+    pub fn new(provider: AccountingProvider, app: OAuthApp, tenant_id: Option<String>, sandbox: bool) -> Self {
+        match provider {
+            AccountingProvider::QuickBooks => AccountingClient::QuickBooks(QuickBooksClient::new(app, tenant_id, sandbox)),
+            AccountingProvider::Xero => AccountingClient::Xero(XeroClient::new(app, tenant_id)),
+        }
+    }
+
+    pub fn authorization_url(&self, state: &str) -> String {
+        match self {
+            AccountingClient::QuickBooks(client) => client.authorization_url(state),
+            AccountingClient::Xero(client) => client.authorization_url(state),
+        }
+    }
+
+    pub async fn exchange_code(&self, code: &str) -> Result<OAuthTokens> {
+        match self {
+            AccountingClient::QuickBooks(client) => client.exchange_code(code).await,
+            AccountingClient::Xero(client) => client.exchange_code(code).await,
+        }
+    }
+
+    pub async fn refresh(&self, refresh_token: &str) -> Result<OAuthTokens> {
+        match self {
+            AccountingClient::QuickBooks(client) => client.refresh(refresh_token).await,
+            AccountingClient::Xero(client) => client.refresh(refresh_token).await,
+        }
+    }
+
+    /// Company the tokens were issued for: the realm ID QuickBooks passed on the redirect,
+    /// or the first tenant connected to the Xero app
+    pub async fn resolve_tenant(&self, access_token: &str, realm_id: Option<&str>) -> Result<String> {
+        match self {
+            AccountingClient::QuickBooks(_) => realm_id
+                .map(str::to_string)
+                .ok_or_else(|| AccountingClientError::Auth("QuickBooks did not return a realm ID".to_string())),
+            AccountingClient::Xero(client) => client.first_tenant(access_token).await,
+        }
+    }
+
+    pub async fn create_invoice(
+        &self,
+        access_token: &str,
+        invoice: &InvoiceDraft,
+        idempotency_key: &str,
+    ) -> Result<CreatedInvoice> {
+        match self {
+            AccountingClient::QuickBooks(client) => client.create_invoice(access_token, invoice, idempotency_key).await,
+            AccountingClient::Xero(client) => client.create_invoice(access_token, invoice, idempotency_key).await,
+        }
+    }
+
+    /// Pay the invoice in full; returns the payment ID
+    pub async fn create_payment(
+        &self,
+        access_token: &str,
+        invoice: &InvoiceDraft,
+        created: &CreatedInvoice,
+        payment_account_ref: Option<&str>,
+        idempotency_key: &str,
+    ) -> Result<String> {
+        match self {
+            AccountingClient::QuickBooks(client) => {
+                client.create_payment(access_token, invoice, created, payment_account_ref, idempotency_key).await
+            }
+            AccountingClient::Xero(client) => {
+                let account = payment_account_ref.ok_or_else(|| {
+                    AccountingClientError::Config("Xero payments need a bank account code".to_string())
+                })?;
+                client.create_payment(access_token, invoice, created, account, idempotency_key).await
+            }
+        }
+    }
+}
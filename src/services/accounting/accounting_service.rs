@@ -0,0 +1,649 @@
+// Accounting Service
+// QuickBooks Online / Xero connections (OAuth2 authorization, token refresh on use) and
+// the export of completed marketplace sales: an invoice in the seller's books billed to
+// the configured customer/contact, optionally followed by a payment applied to it.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use chrono::{DateTime, Duration, Utc};
+use rand::RngCore;
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::accounting_client::{
+    AccountingClient, AccountingClientError, CreatedInvoice, InvoiceDraft, OAuthApp, OAuthTokens,
+};
+use crate::middleware::error_handling::{AppError, Result};
+use crate::models::accounting::{
+    AccountingAuthorizationResponse, AccountingConnection, AccountingConnectionResponse, AccountingExport,
+    AccountingProvider, CompleteAccountingAuthorizationRequest, CreateAccountingConnectionRequest,
+    UpdateAccountingSettingsRequest,
+};
+use crate::services::encryption_service::EncryptionService;
+
+const CONNECTION_COLUMNS: &str = "id, user_id, provider, connection_name, status, sandbox, client_id, client_secret, \
+    access_token, refresh_token, token_expires_at, tenant_id, export_invoices, record_payments, customer_ref, \
+    item_ref, payment_account_ref, last_error, last_export_at, created_at, updated_at";
+
+const EXPORT_COLUMNS: &str = "id, accounting_connection_id, transaction_id, status, invoice_id, invoice_number, \
+    payment_id, attempts, error_message, created_at, updated_at";
+
+/// How long the user has to approve access after starting authorization
+const STATE_TTL_MINUTES: i64 = 15;
+/// Access tokens are refreshed when they expire within this margin
+const TOKEN_REFRESH_MARGIN_SECONDS: i64 = 300;
+
+#[derive(sqlx::FromRow)]
+struct SaleSource {
+    transaction_id: Uuid,
+    seller_id: Uuid,
+    quantity: i32,
+    unit_price: Decimal,
+    total_price: Decimal,
+    status: Option<String>,
+    transaction_date: Option<DateTime<Utc>>,
+    brand_name: String,
+    generic_name: String,
+    strength: Option<String>,
+    buyer_name: String,
+}
+
+impl SaleSource {
+    /// Invoice billing the sale to the connection's customer/contact and item/account
+    fn invoice_draft(&self, customer_ref: &str, item_ref: &str) -> InvoiceDraft {
+        InvoiceDraft {
+            reference: format!("ATLAS-{}", &self.transaction_id.simple().to_string()[..12]),
+            date: self.transaction_date.unwrap_or_else(Utc::now).date_naive(),
+            customer_ref: customer_ref.to_string(),
+            item_ref: item_ref.to_string(),
+            description: format!(
+                "{} ({}){} - Atlas marketplace sale to {}",
+                self.brand_name,
+                self.generic_name,
+                self.strength.as_deref().map(|s| format!(" {}", s)).unwrap_or_default(),
+                self.buyer_name
+            ),
+            quantity: self.quantity,
+            unit_price: self.unit_price,
+            total: self.total_price,
+        }
+    }
+}
+
+/// Random single-use OAuth state; only its lookup hash is stored
+fn new_oauth_state() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// Whether an access token expiring at `expires_at` must be refreshed before use. A token
+/// of unknown expiry is always refreshed.
+fn needs_refresh(expires_at: Option<DateTime<Utc>>, now: DateTime<Utc>) -> bool {
+    expires_at
+        .map(|expires_at| expires_at - now <= Duration::seconds(TOKEN_REFRESH_MARGIN_SECONDS))
+        .unwrap_or(true)
+}
+
+impl From<AccountingClientError> for AppError {
+    fn from(err: AccountingClientError) -> Self {
+        match err {
+            AccountingClientError::Auth(message) => AppError::BadRequest(message),
+            AccountingClientError::Config(message) => AppError::BadRequest(message),
+            other => AppError::Internal(anyhow::anyhow!("{}", other)),
+        }
+    }
+}
+
+pub struct AccountingService {
+    pool: PgPool,
+    encryption_service: EncryptionService,
+    redirect_uri: Option<String>,
+}
+
+impl AccountingService {
+    pub fn new(pool: PgPool) -> Result<Self> {
+        let encryption_key = std::env::var("ENCRYPTION_KEY")
+            .map_err(|_| AppError::Internal(anyhow::anyhow!("ENCRYPTION_KEY not set")))?;
+        let encryption_service = EncryptionService::new(&encryption_key)?;
+
+        Ok(Self {
+            pool,
+            encryption_service,
+            redirect_uri: std::env::var("ACCOUNTING_OAUTH_REDIRECT_URI").ok(),
+        })
+    }
+
+    // ========================================================================
+    // Connections
+    // ========================================================================
+
+    pub async fn list_connections(&self, user_id: Uuid) -> Result<Vec<AccountingConnection>> {
+        let connections = sqlx::query_as::<_, AccountingConnection>(&format!(
+            "SELECT {} FROM accounting_connections WHERE user_id = $1 ORDER BY created_at",
+            CONNECTION_COLUMNS
+        ))
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(connections)
+    }
+
+    /// The user's connection; other users' connections are reported as not found
+    pub async fn get_connection(&self, connection_id: Uuid, user_id: Uuid) -> Result<AccountingConnection> {
+        sqlx::query_as::<_, AccountingConnection>(&format!(
+            "SELECT {} FROM accounting_connections WHERE id = $1 AND user_id = $2",
+            CONNECTION_COLUMNS
+        ))
+        .bind(connection_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Accounting connection not found".to_string()))
+    }
+
+    /// Store the OAuth2 app and start authorization
+    pub async fn create_connection(
+        &self,
+        user_id: Uuid,
+        request: CreateAccountingConnectionRequest,
+    ) -> Result<AccountingAuthorizationResponse> {
+        let connection = sqlx::query_as::<_, AccountingConnection>(&format!(
+            r#"
+            INSERT INTO accounting_connections (user_id, provider, connection_name, sandbox, client_id, client_secret)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING {}
+            "#,
+            CONNECTION_COLUMNS
+        ))
+        .bind(user_id)
+        .bind(request.provider.as_str())
+        .bind(request.connection_name.trim())
+        .bind(request.sandbox.unwrap_or(false) && request.provider == AccountingProvider::QuickBooks)
+        .bind(self.encryption_service.encrypt(request.client_id.trim())?)
+        .bind(self.encryption_service.encrypt(request.client_secret.trim())?)
+        .fetch_one(&self.pool)
+        .await?;
+
+        self.start_authorization(&connection).await
+    }
+
+    /// Issue a fresh state and the provider URL the user approves access at. Also used to
+    /// re-authorize a connection whose refresh token was revoked or expired.
+    pub async fn start_authorization(&self, connection: &AccountingConnection) -> Result<AccountingAuthorizationResponse> {
+        let state = new_oauth_state();
+
+        let connection = sqlx::query_as::<_, AccountingConnection>(&format!(
+            r#"
+            UPDATE accounting_connections
+            SET oauth_state_hash = $2, oauth_state_expires_at = $3, updated_at = NOW()
+            WHERE id = $1
+            RETURNING {}
+            "#,
+            CONNECTION_COLUMNS
+        ))
+        .bind(connection.id)
+        .bind(EncryptionService::hash_for_lookup(&state))
+        .bind(Utc::now() + Duration::minutes(STATE_TTL_MINUTES))
+        .fetch_one(&self.pool)
+        .await?;
+
+        let client = self.client(&connection)?;
+
+        Ok(AccountingAuthorizationResponse {
+            authorization_url: client.authorization_url(&state),
+            connection: AccountingConnectionResponse::from(&connection),
+        })
+    }
+
+    /// Exchange the authorization code for tokens and activate the connection
+    pub async fn complete_authorization(
+        &self,
+        connection: &AccountingConnection,
+        request: &CompleteAccountingAuthorizationRequest,
+    ) -> Result<AccountingConnection> {
+        // The state is single use
+        let valid: Option<Uuid> = sqlx::query_scalar(
+            r#"
+            UPDATE accounting_connections
+            SET oauth_state_hash = NULL, oauth_state_expires_at = NULL
+            WHERE id = $1 AND oauth_state_hash = $2 AND oauth_state_expires_at > NOW()
+            RETURNING id
+            "#
+        )
+        .bind(connection.id)
+        .bind(EncryptionService::hash_for_lookup(&request.state))
+        .fetch_optional(&self.pool)
+        .await?;
+        if valid.is_none() {
+            return Err(AppError::BadRequest("Invalid or expired authorization state".to_string()));
+        }
+
+        let client = self.client(connection)?;
+        let tokens = client.exchange_code(&request.code).await?;
+        let tenant_id = client.resolve_tenant(&tokens.access_token, request.realm_id.as_deref()).await?;
+
+        let connection = sqlx::query_as::<_, AccountingConnection>(&format!(
+            r#"
+            UPDATE accounting_connections
+            SET access_token = $2, refresh_token = $3, token_expires_at = $4, tenant_id = $5,
+                status = 'active', last_error = NULL, updated_at = NOW()
+            WHERE id = $1
+            RETURNING {}
+            "#,
+            CONNECTION_COLUMNS
+        ))
+        .bind(connection.id)
+        .bind(self.encryption_service.encrypt(&tokens.access_token)?)
+        .bind(self.encryption_service.encrypt(&tokens.refresh_token)?)
+        .bind(tokens.expires_at)
+        .bind(&tenant_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(connection)
+    }
+
+    pub async fn update_settings(
+        &self,
+        connection: &AccountingConnection,
+        request: &UpdateAccountingSettingsRequest,
+    ) -> Result<AccountingConnection> {
+        let clean = |value: &Option<String>| value.as_deref().map(str::trim).filter(|v| !v.is_empty()).map(str::to_string);
+        let customer_ref = clean(&request.customer_ref);
+        let item_ref = clean(&request.item_ref);
+        let payment_account_ref = clean(&request.payment_account_ref);
+
+        if request.export_invoices && (customer_ref.is_none() || item_ref.is_none()) {
+            return Err(AppError::BadRequest(
+                "customer_ref and item_ref are required to export invoices".to_string(),
+            ));
+        }
+        if request.record_payments && !request.export_invoices {
+            return Err(AppError::BadRequest("record_payments requires export_invoices".to_string()));
+        }
+        if request.record_payments && connection.provider == "xero" && payment_account_ref.is_none() {
+            return Err(AppError::BadRequest(
+                "payment_account_ref (bank account code) is required to record Xero payments".to_string(),
+            ));
+        }
+
+        let connection = sqlx::query_as::<_, AccountingConnection>(&format!(
+            r#"
+            UPDATE accounting_connections
+            SET export_invoices = $2, record_payments = $3, customer_ref = $4, item_ref = $5,
+                payment_account_ref = $6, updated_at = NOW()
+            WHERE id = $1
+            RETURNING {}
+            "#,
+            CONNECTION_COLUMNS
+        ))
+        .bind(connection.id)
+        .bind(request.export_invoices)
+        .bind(request.record_payments)
+        .bind(customer_ref)
+        .bind(item_ref)
+        .bind(payment_account_ref)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(connection)
+    }
+
+    pub async fn delete_connection(&self, connection_id: Uuid, user_id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM accounting_connections WHERE id = $1 AND user_id = $2")
+            .bind(connection_id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    fn client(&self, connection: &AccountingConnection) -> Result<AccountingClient> {
+        let provider = AccountingProvider::parse(&connection.provider)
+            .ok_or_else(|| AppError::Internal(anyhow::anyhow!("Unknown accounting provider {}", connection.provider)))?;
+        let redirect_uri = self.redirect_uri.clone()
+            .ok_or_else(|| AppError::Internal(anyhow::anyhow!("ACCOUNTING_OAUTH_REDIRECT_URI not set")))?;
+
+        let app = OAuthApp {
+            client_id: self.encryption_service.decrypt(&connection.client_id)?,
+            client_secret: self.encryption_service.decrypt(&connection.client_secret)?,
+            redirect_uri,
+        };
+
+        Ok(AccountingClient::new(provider, app, connection.tenant_id.clone(), connection.sandbox))
+    }
+
+    /// A valid access token, refreshing (and storing the rotated refresh token) when the
+    /// current one is about to expire. A rejected refresh puts the connection in error
+    /// until the user re-authorizes.
+    async fn access_token(&self, connection: &AccountingConnection, client: &AccountingClient) -> Result<String> {
+        let (Some(access_token), Some(refresh_token)) = (&connection.access_token, &connection.refresh_token) else {
+            return Err(AppError::BadRequest("Accounting connection is not authorized".to_string()));
+        };
+
+        if !needs_refresh(connection.token_expires_at, Utc::now()) {
+            return Ok(self.encryption_service.decrypt(access_token)?);
+        }
+
+        let tokens: OAuthTokens = match client.refresh(&self.encryption_service.decrypt(refresh_token)?).await {
+            Ok(tokens) => tokens,
+            Err(e) => {
+                if matches!(e, AccountingClientError::Auth(_)) {
+                    self.set_connection_error(connection.id, "error", &e.to_string()).await?;
+                }
+                return Err(e.into());
+            }
+        };
+
+        sqlx::query(
+            r#"
+            UPDATE accounting_connections
+            SET access_token = $2, refresh_token = $3, token_expires_at = $4, updated_at = NOW()
+            WHERE id = $1
+            "#
+        )
+        .bind(connection.id)
+        .bind(self.encryption_service.encrypt(&tokens.access_token)?)
+        .bind(self.encryption_service.encrypt(&tokens.refresh_token)?)
+        .bind(tokens.expires_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(tokens.access_token)
+    }
+
+    async fn set_connection_error(&self, connection_id: Uuid, status: &str, error: &str) -> Result<()> {
+        sqlx::query(
+            "UPDATE accounting_connections SET status = $2, last_error = $3, updated_at = NOW() WHERE id = $1"
+        )
+        .bind(connection_id)
+        .bind(status)
+        .bind(error)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    // ========================================================================
+    // Invoice Export
+    // ========================================================================
+
+    /// Push a completed sale to the seller's active accounting connections. Invoices
+    /// already created are not created again; a failed connection is recorded on its
+    /// export and does not stop the others.
+    pub async fn export_transaction(&self, transaction_id: Uuid) -> Result<Vec<AccountingExport>> {
+        let source = sqlx::query_as::<_, SaleSource>(
+            r#"
+            SELECT
+                t.id AS transaction_id, t.seller_id, t.quantity, t.unit_price, t.total_price,
+                t.status, t.transaction_date,
+                p.brand_name, p.generic_name, p.strength,
+                b.company_name AS buyer_name
+            FROM transactions t
+            JOIN inquiries q ON q.id = t.inquiry_id
+            JOIN inventory i ON i.id = q.inventory_id
+            JOIN pharmaceuticals p ON p.id = i.pharmaceutical_id
+            JOIN users b ON b.id = t.buyer_id
+            WHERE t.id = $1
+            "#
+        )
+        .bind(transaction_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Transaction {} not found", transaction_id)))?;
+
+        if source.status.as_deref() != Some("completed") {
+            return Err(AppError::BadRequest(format!("Transaction {} is not completed", transaction_id)));
+        }
+
+        let connections = sqlx::query_as::<_, AccountingConnection>(&format!(
+            "SELECT {} FROM accounting_connections WHERE user_id = $1 AND status = 'active' AND export_invoices = true",
+            CONNECTION_COLUMNS
+        ))
+        .bind(source.seller_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut exports = Vec::new();
+        for connection in connections {
+            exports.push(self.export_to(&connection, &source).await?);
+        }
+
+        Ok(exports)
+    }
+
+    /// Exports of a transaction on the user's own connections
+    pub async fn get_exports(&self, transaction_id: Uuid, user_id: Uuid) -> Result<Vec<AccountingExport>> {
+        let exports = sqlx::query_as::<_, AccountingExport>(&format!(
+            r#"
+            SELECT {} FROM accounting_exports e
+            WHERE e.transaction_id = $1
+              AND EXISTS (SELECT 1 FROM accounting_connections c WHERE c.id = e.accounting_connection_id AND c.user_id = $2)
+            ORDER BY e.created_at
+            "#,
+            EXPORT_COLUMNS
+        ))
+        .bind(transaction_id)
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(exports)
+    }
+
+    async fn export_to(&self, connection: &AccountingConnection, source: &SaleSource) -> Result<AccountingExport> {
+        // Claim the export unless it is already complete (paid, or invoiced without payments)
+        let claimed = sqlx::query_as::<_, AccountingExport>(&format!(
+            r#"
+            INSERT INTO accounting_exports (accounting_connection_id, transaction_id, attempts)
+            VALUES ($1, $2, 1)
+            ON CONFLICT (accounting_connection_id, transaction_id) DO UPDATE
+            SET attempts = accounting_exports.attempts + 1, updated_at = NOW()
+            WHERE accounting_exports.status IN ('pending', 'failed')
+               OR (accounting_exports.status = 'invoiced' AND $3)
+            RETURNING {}
+            "#,
+            EXPORT_COLUMNS
+        ))
+        .bind(connection.id)
+        .bind(source.transaction_id)
+        .bind(connection.record_payments)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let export = match claimed {
+            Some(export) => export,
+            None => {
+                return sqlx::query_as::<_, AccountingExport>(&format!(
+                    "SELECT {} FROM accounting_exports WHERE accounting_connection_id = $1 AND transaction_id = $2",
+                    EXPORT_COLUMNS
+                ))
+                .bind(connection.id)
+                .bind(source.transaction_id)
+                .fetch_one(&self.pool)
+                .await
+                .map_err(AppError::Database);
+            }
+        };
+
+        match self.send(connection, source, &export).await {
+            Ok(export) => {
+                sqlx::query(
+                    "UPDATE accounting_connections SET last_export_at = NOW(), last_error = NULL WHERE id = $1"
+                )
+                .bind(connection.id)
+                .execute(&self.pool)
+                .await?;
+
+                Ok(export)
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "Accounting export to {} failed for transaction {}: {}",
+                    connection.provider, source.transaction_id, e
+                );
+                sqlx::query("UPDATE accounting_connections SET last_error = $2 WHERE id = $1")
+                    .bind(connection.id)
+                    .bind(e.to_string())
+                    .execute(&self.pool)
+                    .await?;
+
+                self.update_export(export.id, "failed", None, None, None, Some(&e.to_string())).await
+            }
+        }
+    }
+
+    /// Create the invoice (unless a previous attempt did) and, if enabled, the payment
+    async fn send(
+        &self,
+        connection: &AccountingConnection,
+        source: &SaleSource,
+        export: &AccountingExport,
+    ) -> Result<AccountingExport> {
+        let (Some(customer_ref), Some(item_ref)) = (&connection.customer_ref, &connection.item_ref) else {
+            return Err(AppError::BadRequest("customer_ref and item_ref must be configured".to_string()));
+        };
+
+        let draft = source.invoice_draft(customer_ref, item_ref);
+
+        let client = self.client(connection)?;
+        let access_token = self.access_token(connection, &client).await?;
+
+        let mut export = export.clone();
+        let invoice = match &export.invoice_id {
+            Some(invoice_id) => CreatedInvoice {
+                id: invoice_id.clone(),
+                number: export.invoice_number.clone(),
+            },
+            None => {
+                let invoice = client.create_invoice(&access_token, &draft, &export.id.to_string()).await?;
+                tracing::info!(
+                    "Created {} invoice {} for transaction {}",
+                    connection.provider, invoice.id, source.transaction_id
+                );
+                export = self.update_export(
+                    export.id, "invoiced", Some(&invoice.id), invoice.number.as_deref(), None, None,
+                ).await?;
+                invoice
+            }
+        };
+
+        if connection.record_payments && export.payment_id.is_none() {
+            let payment_id = client.create_payment(
+                &access_token,
+                &draft,
+                &invoice,
+                connection.payment_account_ref.as_deref(),
+                &format!("{}-payment", export.id),
+            ).await?;
+            export = self.update_export(export.id, "paid", None, None, Some(&payment_id), None).await?;
+        }
+
+        Ok(export)
+    }
+
+    /// Set the status; document IDs are only ever filled in, never cleared
+    async fn update_export(
+        &self,
+        export_id: Uuid,
+        status: &str,
+        invoice_id: Option<&str>,
+        invoice_number: Option<&str>,
+        payment_id: Option<&str>,
+        error_message: Option<&str>,
+    ) -> Result<AccountingExport> {
+        let export = sqlx::query_as::<_, AccountingExport>(&format!(
+            r#"
+            UPDATE accounting_exports
+            SET status = $2,
+                invoice_id = COALESCE($3, invoice_id),
+                invoice_number = COALESCE($4, invoice_number),
+                payment_id = COALESCE($5, payment_id),
+                error_message = $6,
+                updated_at = NOW()
+            WHERE id = $1
+            RETURNING {}
+            "#,
+            EXPORT_COLUMNS
+        ))
+        .bind(export_id)
+        .bind(status)
+        .bind(invoice_id)
+        .bind(invoice_number)
+        .bind(payment_id)
+        .bind(error_message)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(export)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn sale() -> SaleSource {
+        SaleSource {
+            transaction_id: Uuid::parse_str("0123456789ab4def8123456789abcdef").unwrap(),
+            seller_id: Uuid::new_v4(),
+            quantity: 24,
+            unit_price: Decimal::new(1250, 2),
+            total_price: Decimal::new(30000, 2),
+            status: Some("completed".to_string()),
+            transaction_date: Some(Utc.with_ymd_and_hms(2026, 3, 1, 23, 30, 0).unwrap()),
+            brand_name: "Humalog".to_string(),
+            generic_name: "insulin lispro".to_string(),
+            strength: Some("100 U/mL".to_string()),
+            buyer_name: "Acme Pharmacy".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_invoice_draft() {
+        let draft = sale().invoice_draft("58", "7");
+
+        assert_eq!(draft.reference, "ATLAS-0123456789ab");
+        assert_eq!(draft.date, chrono::NaiveDate::from_ymd_opt(2026, 3, 1).unwrap());
+        assert_eq!(draft.customer_ref, "58");
+        assert_eq!(draft.item_ref, "7");
+        assert_eq!(draft.description, "Humalog (insulin lispro) 100 U/mL - Atlas marketplace sale to Acme Pharmacy");
+        assert_eq!((draft.quantity, draft.unit_price, draft.total), (24, Decimal::new(1250, 2), Decimal::new(30000, 2)));
+
+        let mut unstrengthened = sale();
+        unstrengthened.strength = None;
+        assert_eq!(
+            unstrengthened.invoice_draft("58", "7").description,
+            "Humalog (insulin lispro) - Atlas marketplace sale to Acme Pharmacy"
+        );
+    }
+
+    #[test]
+    fn test_oauth_state() {
+        let state = new_oauth_state();
+        // 32 random bytes, URL-safe so it survives the redirect unescaped
+        assert_eq!(state.len(), 43);
+        assert!(state.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        assert_ne!(state, new_oauth_state());
+        // Only the hash is stored, and the callback's state is looked up by it
+        assert_eq!(EncryptionService::hash_for_lookup(&state), EncryptionService::hash_for_lookup(&state));
+        assert_ne!(EncryptionService::hash_for_lookup(&state), EncryptionService::hash_for_lookup(&new_oauth_state()));
+    }
+
+    #[test]
+    fn test_needs_refresh() {
+        let now = Utc::now();
+
+        assert!(!needs_refresh(Some(now + Duration::hours(1)), now));
+        assert!(!needs_refresh(Some(now + Duration::seconds(TOKEN_REFRESH_MARGIN_SECONDS + 1)), now));
+        // Tokens about to expire are refreshed before the call, not after it fails
+        assert!(needs_refresh(Some(now + Duration::seconds(TOKEN_REFRESH_MARGIN_SECONDS)), now));
+        assert!(needs_refresh(Some(now - Duration::minutes(1)), now));
+        assert!(needs_refresh(None, now));
+    }
+}
@@ -0,0 +1,12 @@
+// Accounting Module
+// Lightweight QuickBooks Online / Xero connectors, separate from the ERP inventory sync:
+// OAuth2 clients for both providers and the service that pushes completed marketplace
+// sales as invoices and payments
+
+pub mod accounting_client;
+pub mod quickbooks_client;
+pub mod xero_client;
+pub mod accounting_service;
+
+pub use accounting_client::{AccountingClient, AccountingClientError, InvoiceDraft, OAuthApp, OAuthTokens};
+pub use accounting_service::AccountingService;
@@ -0,0 +1,262 @@
+// QuickBooks Online client
+// OAuth2 against Intuit's identity platform and the Accounting API v3. Writes carry a
+// `requestid`, which QuickBooks uses to de-duplicate retried requests.
+
+use rust_decimal::prelude::ToPrimitive;
+use serde::Deserialize;
+
+use super::accounting_client::{
+    check_status, request_tokens, AccountingClientError, CreatedInvoice, InvoiceDraft, OAuthApp, OAuthTokens, Result,
+    REQUEST_TIMEOUT,
+};
+
+const PROVIDER: &str = "QuickBooks";
+const AUTHORIZE_URL: &str = "https://appcenter.intuit.com/connect/oauth2";
+const TOKEN_URL: &str = "https://oauth.platform.intuit.com/oauth2/v1/tokens/bearer";
+const SCOPE: &str = "com.intuit.quickbooks.accounting";
+const PRODUCTION_API: &str = "https://quickbooks.api.intuit.com";
+const SANDBOX_API: &str = "https://sandbox-quickbooks.api.intuit.com";
+const MINOR_VERSION: &str = "65";
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct InvoiceResponse {
+    invoice: QboDocument,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct PaymentResponse {
+    payment: QboDocument,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct QboDocument {
+    id: String,
+    doc_number: Option<String>,
+}
+
+pub struct QuickBooksClient {
+    http: reqwest::Client,
+    app: OAuthApp,
+    realm_id: Option<String>,
+    sandbox: bool,
+}
+
+impl QuickBooksClient {
+    pub fn new(app: OAuthApp, realm_id: Option<String>, sandbox: bool) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+
+        Self { http, app, realm_id, sandbox }
+    }
+
+    pub fn authorization_url(&self, state: &str) -> String {
+        let mut url = url::Url::parse(AUTHORIZE_URL).expect("valid authorize URL");
+        url.query_pairs_mut()
+            .append_pair("client_id", &self.app.client_id)
+            .append_pair("response_type", "code")
+            .append_pair("scope", SCOPE)
+            .append_pair("redirect_uri", &self.app.redirect_uri)
+            .append_pair("state", state);
+        url.to_string()
+    }
+
+    pub async fn exchange_code(&self, code: &str) -> Result<OAuthTokens> {
+        request_tokens(&self.http, PROVIDER, TOKEN_URL, &self.app, &[
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", &self.app.redirect_uri),
+        ])
+        .await
+    }
+
+    pub async fn refresh(&self, refresh_token: &str) -> Result<OAuthTokens> {
+        request_tokens(&self.http, PROVIDER, TOKEN_URL, &self.app, &[
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token),
+        ])
+        .await
+    }
+
+    pub async fn create_invoice(
+        &self,
+        access_token: &str,
+        invoice: &InvoiceDraft,
+        request_id: &str,
+    ) -> Result<CreatedInvoice> {
+        let payload = invoice_payload(invoice);
+        let response: InvoiceResponse = self.post(access_token, "invoice", &payload, request_id).await?;
+
+        Ok(CreatedInvoice {
+            id: response.invoice.id,
+            number: response.invoice.doc_number,
+        })
+    }
+
+    /// Receive payment for the invoice; without a deposit account QuickBooks books it to
+    /// Undeposited Funds
+    pub async fn create_payment(
+        &self,
+        access_token: &str,
+        invoice: &InvoiceDraft,
+        created: &CreatedInvoice,
+        deposit_account: Option<&str>,
+        request_id: &str,
+    ) -> Result<String> {
+        let payload = payment_payload(invoice, created, deposit_account);
+        let response: PaymentResponse = self.post(access_token, "payment", &payload, request_id).await?;
+        Ok(response.payment.id)
+    }
+
+    async fn post<T: serde::de::DeserializeOwned>(
+        &self,
+        access_token: &str,
+        entity: &str,
+        payload: &serde_json::Value,
+        request_id: &str,
+    ) -> Result<T> {
+        let realm_id = self.realm_id.as_deref()
+            .ok_or_else(|| AccountingClientError::Config("QuickBooks connection is not authorized".to_string()))?;
+        let base = if self.sandbox { SANDBOX_API } else { PRODUCTION_API };
+
+        let response = self
+            .http
+            .post(format!("{}/v3/company/{}/{}", base, realm_id, entity))
+            .bearer_auth(access_token)
+            .header("Accept", "application/json")
+            .query(&[("minorversion", MINOR_VERSION), ("requestid", request_id)])
+            .json(payload)
+            .send()
+            .await?;
+
+        check_status(PROVIDER, response)
+            .await?
+            .json()
+            .await
+            .map_err(|e| AccountingClientError::InvalidResponse(e.to_string()))
+    }
+}
+
+/// Invoice request body with the invoice's single sales item line
+fn invoice_payload(invoice: &InvoiceDraft) -> serde_json::Value {
+    serde_json::json!({
+        "DocNumber": truncate(&invoice.reference, 21),
+        "TxnDate": invoice.date.format("%Y-%m-%d").to_string(),
+        "CustomerRef": { "value": invoice.customer_ref },
+        "PrivateNote": invoice.reference,
+        "Line": [{
+            "Amount": invoice.total.to_f64().unwrap_or(0.0),
+            "Description": invoice.description,
+            "DetailType": "SalesItemLineDetail",
+            "SalesItemLineDetail": {
+                "ItemRef": { "value": invoice.item_ref },
+                "Qty": invoice.quantity,
+                "UnitPrice": invoice.unit_price.to_f64().unwrap_or(0.0),
+            },
+        }],
+    })
+}
+
+/// Payment request body applying the invoice total to the invoice
+fn payment_payload(invoice: &InvoiceDraft, created: &CreatedInvoice, deposit_account: Option<&str>) -> serde_json::Value {
+    let amount = invoice.total.to_f64().unwrap_or(0.0);
+    let mut payload = serde_json::json!({
+        "TotalAmt": amount,
+        "TxnDate": invoice.date.format("%Y-%m-%d").to_string(),
+        "CustomerRef": { "value": invoice.customer_ref },
+        "PaymentRefNum": truncate(&invoice.reference, 21),
+        "Line": [{
+            "Amount": amount,
+            "LinkedTxn": [{ "TxnId": created.id, "TxnType": "Invoice" }],
+        }],
+    });
+    if let Some(account) = deposit_account {
+        payload["DepositToAccountRef"] = serde_json::json!({ "value": account });
+    }
+    payload
+}
+
+/// QuickBooks limits DocNumber / PaymentRefNum to 21 characters
+fn truncate(value: &str, max: usize) -> String {
+    value.chars().take(max).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use rust_decimal::Decimal;
+
+    fn draft() -> InvoiceDraft {
+        InvoiceDraft {
+            reference: "ATLAS-0123456789abcdef".to_string(),
+            date: NaiveDate::from_ymd_opt(2026, 3, 1).unwrap(),
+            customer_ref: "58".to_string(),
+            item_ref: "7".to_string(),
+            description: "Humalog (insulin lispro) 100 U/mL - Atlas marketplace sale to Acme".to_string(),
+            quantity: 24,
+            unit_price: Decimal::new(1250, 2),
+            total: Decimal::new(30000, 2),
+        }
+    }
+
+    fn app() -> OAuthApp {
+        OAuthApp {
+            client_id: "client".to_string(),
+            client_secret: "secret".to_string(),
+            redirect_uri: "https://atlas.example/accounting/callback".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_authorization_url() {
+        let url = url::Url::parse(&QuickBooksClient::new(app(), None, false).authorization_url("a+b/c=")).unwrap();
+        let query: std::collections::HashMap<_, _> = url.query_pairs().into_owned().collect();
+
+        assert!(url.as_str().starts_with(AUTHORIZE_URL));
+        assert_eq!(query["client_id"], "client");
+        assert_eq!(query["response_type"], "code");
+        assert_eq!(query["scope"], SCOPE);
+        assert_eq!(query["redirect_uri"], "https://atlas.example/accounting/callback");
+        // The state comes back unchanged however it is encoded
+        assert_eq!(query["state"], "a+b/c=");
+    }
+
+    #[test]
+    fn test_invoice_payload() {
+        let payload = invoice_payload(&draft());
+
+        // DocNumber is cut to QuickBooks' 21 characters; the full reference stays in the note
+        assert_eq!(payload["DocNumber"], "ATLAS-0123456789abcde");
+        assert_eq!(payload["PrivateNote"], "ATLAS-0123456789abcdef");
+        assert_eq!(payload["TxnDate"], "2026-03-01");
+        assert_eq!(payload["CustomerRef"]["value"], "58");
+        let line = &payload["Line"][0];
+        assert_eq!(line["Amount"], 300.0);
+        assert_eq!(line["DetailType"], "SalesItemLineDetail");
+        assert_eq!(line["SalesItemLineDetail"]["ItemRef"]["value"], "7");
+        assert_eq!(line["SalesItemLineDetail"]["Qty"], 24);
+        assert_eq!(line["SalesItemLineDetail"]["UnitPrice"], 12.5);
+    }
+
+    #[test]
+    fn test_payment_payload() {
+        let created = CreatedInvoice { id: "130".to_string(), number: Some("1037".to_string()) };
+
+        let payload = payment_payload(&draft(), &created, Some("35"));
+        assert_eq!(payload["TotalAmt"], 300.0);
+        assert_eq!(payload["PaymentRefNum"], "ATLAS-0123456789abcde");
+        assert_eq!(payload["Line"][0]["Amount"], 300.0);
+        assert_eq!(payload["Line"][0]["LinkedTxn"][0]["TxnId"], "130");
+        assert_eq!(payload["Line"][0]["LinkedTxn"][0]["TxnType"], "Invoice");
+        assert_eq!(payload["DepositToAccountRef"]["value"], "35");
+
+        // Without a deposit account QuickBooks uses Undeposited Funds
+        let payload = payment_payload(&draft(), &created, None);
+        assert!(payload.get("DepositToAccountRef").is_none());
+    }
+}
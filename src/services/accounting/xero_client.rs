@@ -0,0 +1,304 @@
+// Xero client
+// OAuth2 against Xero identity and the Accounting API. Every API call is scoped to the
+// tenant (organisation) picked at authorization; writes carry an `Idempotency-Key`.
+
+use rust_decimal::prelude::ToPrimitive;
+use serde::Deserialize;
+
+use super::accounting_client::{
+    check_status, request_tokens, AccountingClientError, CreatedInvoice, InvoiceDraft, OAuthApp, OAuthTokens, Result,
+    REQUEST_TIMEOUT,
+};
+
+const PROVIDER: &str = "Xero";
+const AUTHORIZE_URL: &str = "https://login.xero.com/identity/connect/authorize";
+const TOKEN_URL: &str = "https://identity.xero.com/connect/token";
+const CONNECTIONS_URL: &str = "https://api.xero.com/connections";
+const API_URL: &str = "https://api.xero.com/api.xro/2.0";
+const SCOPE: &str = "offline_access accounting.transactions";
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct XeroTenant {
+    tenant_id: String,
+    tenant_type: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct InvoicesResponse {
+    invoices: Vec<XeroInvoice>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct XeroInvoice {
+    #[serde(rename = "InvoiceID")]
+    invoice_id: String,
+    invoice_number: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct PaymentsResponse {
+    payments: Vec<XeroPayment>,
+}
+
+#[derive(Deserialize)]
+struct XeroPayment {
+    #[serde(rename = "PaymentID")]
+    payment_id: String,
+}
+
+pub struct XeroClient {
+    http: reqwest::Client,
+    app: OAuthApp,
+    tenant_id: Option<String>,
+}
+
+impl XeroClient {
+    pub fn new(app: OAuthApp, tenant_id: Option<String>) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+
+        Self { http, app, tenant_id }
+    }
+
+    pub fn authorization_url(&self, state: &str) -> String {
+        let mut url = url::Url::parse(AUTHORIZE_URL).expect("valid authorize URL");
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &self.app.client_id)
+            .append_pair("redirect_uri", &self.app.redirect_uri)
+            .append_pair("scope", SCOPE)
+            .append_pair("state", state);
+        url.to_string()
+    }
+
+    pub async fn exchange_code(&self, code: &str) -> Result<OAuthTokens> {
+        request_tokens(&self.http, PROVIDER, TOKEN_URL, &self.app, &[
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", &self.app.redirect_uri),
+        ])
+        .await
+    }
+
+    pub async fn refresh(&self, refresh_token: &str) -> Result<OAuthTokens> {
+        request_tokens(&self.http, PROVIDER, TOKEN_URL, &self.app, &[
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token),
+        ])
+        .await
+    }
+
+    /// First organisation the user connected to the app
+    pub async fn first_tenant(&self, access_token: &str) -> Result<String> {
+        let response = self
+            .http
+            .get(CONNECTIONS_URL)
+            .bearer_auth(access_token)
+            .header("Accept", "application/json")
+            .send()
+            .await?;
+
+        let tenants: Vec<XeroTenant> = check_status(PROVIDER, response)
+            .await?
+            .json()
+            .await
+            .map_err(|e| AccountingClientError::InvalidResponse(e.to_string()))?;
+
+        tenants
+            .into_iter()
+            .find(|t| t.tenant_type.as_deref().map(|kind| kind == "ORGANISATION").unwrap_or(true))
+            .map(|t| t.tenant_id)
+            .ok_or_else(|| AccountingClientError::Auth("No Xero organisation was connected".to_string()))
+    }
+
+    /// Authorised sales invoice (ACCREC), due on the sale date
+    pub async fn create_invoice(
+        &self,
+        access_token: &str,
+        invoice: &InvoiceDraft,
+        idempotency_key: &str,
+    ) -> Result<CreatedInvoice> {
+        let payload = invoice_payload(invoice);
+        let response = self
+            .request(reqwest::Method::POST, "Invoices", access_token, idempotency_key)?
+            .json(&payload)
+            .send()
+            .await?;
+
+        let created: InvoicesResponse = check_status(PROVIDER, response)
+            .await?
+            .json()
+            .await
+            .map_err(|e| AccountingClientError::InvalidResponse(e.to_string()))?;
+
+        let invoice = created
+            .invoices
+            .into_iter()
+            .next()
+            .ok_or_else(|| AccountingClientError::InvalidResponse("Xero returned no invoice".to_string()))?;
+
+        Ok(CreatedInvoice {
+            id: invoice.invoice_id,
+            number: invoice.invoice_number,
+        })
+    }
+
+    /// Payment of the full invoice amount into a bank account
+    pub async fn create_payment(
+        &self,
+        access_token: &str,
+        invoice: &InvoiceDraft,
+        created: &CreatedInvoice,
+        account_code: &str,
+        idempotency_key: &str,
+    ) -> Result<String> {
+        let payload = payment_payload(invoice, created, account_code);
+        let response = self
+            .request(reqwest::Method::PUT, "Payments", access_token, idempotency_key)?
+            .json(&payload)
+            .send()
+            .await?;
+
+        let created: PaymentsResponse = check_status(PROVIDER, response)
+            .await?
+            .json()
+            .await
+            .map_err(|e| AccountingClientError::InvalidResponse(e.to_string()))?;
+
+        created
+            .payments
+            .into_iter()
+            .next()
+            .map(|p| p.payment_id)
+            .ok_or_else(|| AccountingClientError::InvalidResponse("Xero returned no payment".to_string()))
+    }
+
+    fn request(
+        &self,
+        method: reqwest::Method,
+        endpoint: &str,
+        access_token: &str,
+        idempotency_key: &str,
+    ) -> Result<reqwest::RequestBuilder> {
+        let tenant_id = self.tenant_id.as_deref()
+            .ok_or_else(|| AccountingClientError::Config("Xero connection is not authorized".to_string()))?;
+
+        Ok(self
+            .http
+            .request(method, format!("{}/{}", API_URL, endpoint))
+            .bearer_auth(access_token)
+            .header("xero-tenant-id", tenant_id)
+            .header("Idempotency-Key", idempotency_key)
+            .header("Accept", "application/json"))
+    }
+}
+
+/// `Invoices` request body with the invoice's single line item
+fn invoice_payload(invoice: &InvoiceDraft) -> serde_json::Value {
+    let date = invoice.date.format("%Y-%m-%d").to_string();
+    serde_json::json!({
+        "Invoices": [{
+            "Type": "ACCREC",
+            "Contact": { "ContactID": invoice.customer_ref },
+            "Date": date,
+            "DueDate": date,
+            "Reference": invoice.reference,
+            "Status": "AUTHORISED",
+            "LineAmountTypes": "Exclusive",
+            "LineItems": [{
+                "Description": invoice.description,
+                "Quantity": invoice.quantity,
+                "UnitAmount": invoice.unit_price.to_f64().unwrap_or(0.0),
+                "AccountCode": invoice.item_ref,
+            }],
+        }],
+    })
+}
+
+/// `Payments` request body applying the invoice total
+fn payment_payload(invoice: &InvoiceDraft, created: &CreatedInvoice, account_code: &str) -> serde_json::Value {
+    serde_json::json!({
+        "Payments": [{
+            "Invoice": { "InvoiceID": created.id },
+            "Account": { "Code": account_code },
+            "Date": invoice.date.format("%Y-%m-%d").to_string(),
+            "Amount": invoice.total.to_f64().unwrap_or(0.0),
+            "Reference": invoice.reference,
+        }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use rust_decimal::Decimal;
+
+    fn draft() -> InvoiceDraft {
+        InvoiceDraft {
+            reference: "ATLAS-0123456789ab".to_string(),
+            date: NaiveDate::from_ymd_opt(2026, 3, 1).unwrap(),
+            customer_ref: "7ea3c9a2-5c1f-4e0b-9d8e-1f2a3b4c5d6e".to_string(),
+            item_ref: "200".to_string(),
+            description: "Humalog (insulin lispro) 100 U/mL - Atlas marketplace sale to Acme".to_string(),
+            quantity: 24,
+            unit_price: Decimal::new(1250, 2),
+            total: Decimal::new(30000, 2),
+        }
+    }
+
+    #[test]
+    fn test_authorization_url() {
+        let app = OAuthApp {
+            client_id: "client".to_string(),
+            client_secret: "secret".to_string(),
+            redirect_uri: "https://atlas.example/accounting/callback".to_string(),
+        };
+        let url = url::Url::parse(&XeroClient::new(app, None).authorization_url("a+b/c=")).unwrap();
+        let query: std::collections::HashMap<_, _> = url.query_pairs().into_owned().collect();
+
+        assert!(url.as_str().starts_with(AUTHORIZE_URL));
+        assert_eq!(query["client_id"], "client");
+        assert_eq!(query["response_type"], "code");
+        // offline_access is what makes Xero issue a refresh token
+        assert_eq!(query["scope"], "offline_access accounting.transactions");
+        assert_eq!(query["redirect_uri"], "https://atlas.example/accounting/callback");
+        assert_eq!(query["state"], "a+b/c=");
+    }
+
+    #[test]
+    fn test_invoice_payload() {
+        let payload = invoice_payload(&draft());
+        let invoice = &payload["Invoices"][0];
+
+        assert_eq!(invoice["Type"], "ACCREC");
+        assert_eq!(invoice["Status"], "AUTHORISED");
+        assert_eq!(invoice["Contact"]["ContactID"], "7ea3c9a2-5c1f-4e0b-9d8e-1f2a3b4c5d6e");
+        assert_eq!(invoice["Date"], "2026-03-01");
+        assert_eq!(invoice["DueDate"], "2026-03-01");
+        assert_eq!(invoice["Reference"], "ATLAS-0123456789ab");
+        let line = &invoice["LineItems"][0];
+        assert_eq!(line["Quantity"], 24);
+        assert_eq!(line["UnitAmount"], 12.5);
+        assert_eq!(line["AccountCode"], "200");
+    }
+
+    #[test]
+    fn test_payment_payload() {
+        let created = CreatedInvoice { id: "inv-1".to_string(), number: Some("INV-0042".to_string()) };
+        let payload = payment_payload(&draft(), &created, "090");
+        let payment = &payload["Payments"][0];
+
+        assert_eq!(payment["Invoice"]["InvoiceID"], "inv-1");
+        assert_eq!(payment["Account"]["Code"], "090");
+        assert_eq!(payment["Date"], "2026-03-01");
+        assert_eq!(payment["Amount"], 300.0);
+        assert_eq!(payment["Reference"], "ATLAS-0123456789ab");
+    }
+}
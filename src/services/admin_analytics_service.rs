@@ -0,0 +1,182 @@
+/// Admin Analytics Service
+///
+/// Platform-wide product metrics for the admin dashboard, bucketed by day, week or
+/// month (Postgres `date_trunc`, UTC). Every bucket in the range is returned, empty
+/// ones included, so charts and exports line up across reports.
+/// - funnel: accounts by registration cohort and how many got verified, listed
+///   inventory and made a (non-cancelled) transaction, with median time to each stage
+/// - active users: distinct sellers (listed or sold) and buyers (inquired or bought)
+/// - GMV: completed transactions by buyer country and product category
+///
+/// Admin and superadmin accounts are left out of the funnel.
+
+use sqlx::PgPool;
+use crate::{
+    middleware::error_handling::Result,
+    models::admin_analytics::*,
+};
+
+/// Bucket starts covering [$1, $2) at interval $3
+const BUCKETS_CTE: &str = r#"
+    buckets AS (
+        SELECT generate_series(
+            date_trunc($3, $1::timestamptz),
+            $2::timestamptz - INTERVAL '1 microsecond',
+            ('1 ' || $3)::interval
+        ) AS bucket
+    )
+"#;
+
+pub struct AdminAnalyticsService {
+    db_pool: PgPool,
+}
+
+impl AdminAnalyticsService {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self { db_pool }
+    }
+
+    /// Registration cohorts and how far each got
+    pub async fn funnel(&self, range: &AnalyticsRange) -> Result<FunnelReport> {
+        let sql = format!(
+            r#"
+            WITH {buckets},
+            accounts AS (
+                SELECT
+                    date_trunc($3, u.created_at) AS cohort,
+                    u.created_at,
+                    u.verified_at,
+                    (SELECT MIN(i.created_at) FROM inventory i WHERE i.user_id = u.id) AS first_listing_at,
+                    (
+                        SELECT MIN(t.transaction_date)
+                        FROM transactions t
+                        WHERE (t.buyer_id = u.id OR t.seller_id = u.id) AND t.status <> 'cancelled'
+                    ) AS first_transaction_at
+                FROM users u
+                WHERE u.role = 'user'::user_role
+                  AND u.created_at >= $1 AND u.created_at < $2
+            )
+            SELECT
+                b.bucket AS cohort,
+                COUNT(a.created_at) AS registered,
+                COUNT(a.verified_at) AS verified,
+                COUNT(a.first_listing_at) AS listed,
+                COUNT(a.first_transaction_at) AS transacted,
+                percentile_cont(0.5) WITHIN GROUP (
+                    ORDER BY EXTRACT(EPOCH FROM a.verified_at - a.created_at) / 3600
+                ) AS median_hours_to_verification,
+                percentile_cont(0.5) WITHIN GROUP (
+                    ORDER BY EXTRACT(EPOCH FROM a.first_listing_at - a.created_at) / 3600
+                ) AS median_hours_to_first_listing,
+                percentile_cont(0.5) WITHIN GROUP (
+                    ORDER BY EXTRACT(EPOCH FROM a.first_transaction_at - a.created_at) / 3600
+                ) AS median_hours_to_first_transaction
+            FROM buckets b
+            LEFT JOIN accounts a ON a.cohort = b.bucket
+            GROUP BY b.bucket
+            ORDER BY b.bucket
+            "#,
+            buckets = BUCKETS_CTE,
+        );
+
+        let cohorts = sqlx::query_as::<_, FunnelCohort>(&sql)
+            .bind(range.from)
+            .bind(range.to)
+            .bind(range.interval.as_str())
+            .fetch_all(&self.db_pool)
+            .await?;
+
+        Ok(FunnelReport {
+            interval: range.interval,
+            from: range.from,
+            to: range.to,
+            totals: FunnelConversion::from_cohorts(&cohorts),
+            cohorts,
+        })
+    }
+
+    /// Distinct active sellers and buyers per bucket
+    pub async fn active_users(&self, range: &AnalyticsRange) -> Result<ActiveUsersReport> {
+        let sql = format!(
+            r#"
+            WITH {buckets},
+            seller_activity AS (
+                SELECT date_trunc($3, created_at) AS bucket, user_id
+                FROM inventory
+                WHERE created_at >= $1 AND created_at < $2
+                UNION
+                SELECT date_trunc($3, transaction_date), seller_id
+                FROM transactions
+                WHERE transaction_date >= $1 AND transaction_date < $2 AND status <> 'cancelled'
+            ),
+            buyer_activity AS (
+                SELECT date_trunc($3, created_at) AS bucket, buyer_id AS user_id
+                FROM inquiries
+                WHERE created_at >= $1 AND created_at < $2
+                UNION
+                SELECT date_trunc($3, transaction_date), buyer_id
+                FROM transactions
+                WHERE transaction_date >= $1 AND transaction_date < $2 AND status <> 'cancelled'
+            )
+            SELECT
+                b.bucket,
+                (SELECT COUNT(DISTINCT s.user_id) FROM seller_activity s WHERE s.bucket = b.bucket) AS active_sellers,
+                (SELECT COUNT(DISTINCT a.user_id) FROM buyer_activity a WHERE a.bucket = b.bucket) AS active_buyers
+            FROM buckets b
+            ORDER BY b.bucket
+            "#,
+            buckets = BUCKETS_CTE,
+        );
+
+        let buckets = sqlx::query_as::<_, ActiveUsersBucket>(&sql)
+            .bind(range.from)
+            .bind(range.to)
+            .bind(range.interval.as_str())
+            .fetch_all(&self.db_pool)
+            .await?;
+
+        Ok(ActiveUsersReport {
+            interval: range.interval,
+            from: range.from,
+            to: range.to,
+            buckets,
+        })
+    }
+
+    /// Completed transaction value per bucket, buyer country and category
+    pub async fn gmv(&self, range: &AnalyticsRange) -> Result<GmvReport> {
+        let buckets = sqlx::query_as::<_, GmvBucket>(
+            r#"
+            SELECT
+                date_trunc($3, t.transaction_date) AS bucket,
+                COALESCE(buyer.country_code, 'unknown') AS country,
+                COALESCE(NULLIF(TRIM(p.category), ''), 'uncategorized') AS category,
+                COUNT(*) AS transactions,
+                SUM(t.total_price) AS gmv
+            FROM transactions t
+            JOIN users buyer ON buyer.id = t.buyer_id
+            JOIN inquiries q ON q.id = t.inquiry_id
+            JOIN inventory i ON i.id = q.inventory_id
+            JOIN pharmaceuticals p ON p.id = i.pharmaceutical_id
+            WHERE t.status = 'completed'
+              AND t.transaction_date >= $1 AND t.transaction_date < $2
+            GROUP BY 1, 2, 3
+            ORDER BY 1, gmv DESC, 2, 3
+            "#
+        )
+        .bind(range.from)
+        .bind(range.to)
+        .bind(range.interval.as_str())
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(GmvReport {
+            interval: range.interval,
+            from: range.from,
+            to: range.to,
+            total_gmv: buckets.iter().map(|b| b.gmv).sum(),
+            total_transactions: buckets.iter().map(|b| b.transactions).sum(),
+            buckets,
+        })
+    }
+}
@@ -15,10 +15,12 @@
 
 use uuid::Uuid;
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 use chrono::{DateTime, Utc};
 use sqlx::Row;
 use anyhow::anyhow;
 use crate::models::user::{User, UserResponse, UserRole};
+use crate::models::verification::QueueLicense;
 use crate::repositories::UserRepository;
 use crate::middleware::error_handling::{Result, AppError};
 use crate::services::comprehensive_audit_service::{
@@ -33,7 +35,8 @@ use crate::services::comprehensive_audit_service::{
 // REQUEST/RESPONSE MODELS
 // ============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct ListUsersQuery {
     pub limit: Option<i64>,
     pub offset: Option<i64>,
@@ -42,7 +45,7 @@ pub struct ListUsersQuery {
     pub search: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ListUsersResponse {
     pub users: Vec<UserResponse>,
     pub total: i64,
@@ -50,7 +53,7 @@ pub struct ListUsersResponse {
     pub offset: i64,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct AdminStatsResponse {
     pub total_users: i64,
     pub verified_users: i64,
@@ -58,11 +61,13 @@ pub struct AdminStatsResponse {
     pub total_admins: i64,
     pub total_inventory_items: i64,
     pub total_transactions: i64,
+    /// When the counts above last changed (they are kept current by triggers)
+    pub counts_updated_at: Option<DateTime<Utc>>,
     pub recent_signups: Vec<RecentSignup>,
     pub system_health: SystemHealth,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct RecentSignup {
     pub id: String,
     pub email: String,
@@ -71,33 +76,35 @@ pub struct RecentSignup {
     pub is_verified: bool,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct SystemHealth {
     pub database_connected: bool,
     pub uptime_seconds: u64,
     pub total_api_calls_today: i64,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct VerifyUserRequest {
     pub verified: bool,
     pub notes: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct ChangeUserRoleRequest {
     pub role: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct VerificationQueueItem {
     pub user: UserResponse,
     pub inventory_count: i64,
     pub transaction_count: i64,
     pub days_waiting: i64,
+    /// The applicant's uploaded licenses, for review with the application
+    pub licenses: Vec<QueueLicense>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct AuditLogResponse {
     pub id: String,
     pub event_type: String,
@@ -111,7 +118,8 @@ pub struct AuditLogResponse {
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct AuditLogQuery {
     pub limit: Option<i64>,
     pub offset: Option<i64>,
@@ -452,6 +460,7 @@ impl AdminService {
                 inventory_count: 0, // TODO: Join with inventory table
                 transaction_count: 0, // TODO: Join with transactions table
                 days_waiting,
+                licenses: Vec::new(),
             }
         }).collect();
 
@@ -492,24 +501,22 @@ impl AdminService {
     ) -> Result<AdminStatsResponse> {
         use sqlx::query;
 
-        // Get user counts
-        let total_users = self.user_repo.count_users(None, None).await?;
-        let verified_users = self.user_repo.count_users(None, Some(true)).await?;
-        let pending_verifications = self.user_repo.count_users(Some(UserRole::User), Some(false)).await?;
-        let total_admins = self.user_repo.count_users(Some(UserRole::Admin), None).await? +
-                           self.user_repo.count_users(Some(UserRole::Superadmin), None).await?;
-
-        // Get inventory count
-        let inventory_row = query("SELECT COUNT(*) as count FROM inventory")
-            .fetch_one(pool)
-            .await?;
-        let total_inventory_items: i64 = inventory_row.try_get("count")?;
-
-        // Get transaction count
-        let transaction_row = query("SELECT COUNT(*) as count FROM transactions")
-            .fetch_one(pool)
-            .await?;
-        let total_transactions: i64 = transaction_row.try_get("count")?;
+        // Counts come from the trigger-maintained summary instead of table scans
+        let counts = query(
+            r#"
+            SELECT
+                COALESCE(MAX(value) FILTER (WHERE name = 'users'), 0) AS total_users,
+                COALESCE(MAX(value) FILTER (WHERE name = 'users_verified'), 0) AS verified_users,
+                COALESCE(MAX(value) FILTER (WHERE name = 'users_pending_verification'), 0) AS pending_verifications,
+                COALESCE(MAX(value) FILTER (WHERE name = 'admins'), 0) AS total_admins,
+                COALESCE(MAX(value) FILTER (WHERE name = 'inventory_items'), 0) AS total_inventory_items,
+                COALESCE(MAX(value) FILTER (WHERE name = 'transactions'), 0) AS total_transactions,
+                MAX(updated_at) AS counts_updated_at
+            FROM summary_counts
+            "#
+        )
+        .fetch_one(pool)
+        .await?;
 
         // Get recent signups (last 7 days)
         let recent_users = query(
@@ -542,12 +549,13 @@ impl AdminService {
         };
 
         Ok(AdminStatsResponse {
-            total_users,
-            verified_users,
-            pending_verifications,
-            total_admins,
-            total_inventory_items,
-            total_transactions,
+            total_users: counts.try_get("total_users")?,
+            verified_users: counts.try_get("verified_users")?,
+            pending_verifications: counts.try_get("pending_verifications")?,
+            total_admins: counts.try_get("total_admins")?,
+            total_inventory_items: counts.try_get("total_inventory_items")?,
+            total_transactions: counts.try_get("total_transactions")?,
+            counts_updated_at: counts.try_get("counts_updated_at")?,
             recent_signups,
             system_health,
         })
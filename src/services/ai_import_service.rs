@@ -5,10 +5,15 @@ use uuid::Uuid;
 use sqlx::PgPool;
 use crate::middleware::error_handling::{Result, AppError};
 use crate::services::claude_ai_service::{ClaudeAIService, ClaudeRequestConfig, user_message};
+use crate::services::ai_response_cache_service::AiFeature;
 use crate::services::file_parser_service::{FileParserService, ParsedFile};
+use crate::services::inventory_validator_service::InventoryValidatorService;
 use crate::models::ai_import::{
-    AiImportSession, ColumnMapping, ImportStatus, MappedInventoryRow,
+    header_fingerprint, rollback_blocker, AiImportMappingMemory, AiImportRowDecision,
+    AiImportSession, ColumnMapping, ImportStatus, MappedInventoryRow, RollbackResult,
+    RollbackSkippedRow, RowDecision, RowDecisionInput, RowPreview, RowPreviewResponse,
 };
+use std::collections::{HashMap, HashSet};
 
 const ANALYSIS_SYSTEM_PROMPT: &str = r#"You are an expert pharmaceutical inventory data analyst. Your task is to analyze supplier data files and map columns to a standardized pharmaceutical inventory schema.
 
@@ -89,26 +94,13 @@ impl AiImportService {
         Ok(session_id)
     }
 
-    /// Step 2: Analyze uploaded file with Claude AI
-    pub async fn analyze_file(
-        &self,
-        session_id: Uuid,
-        file_data: Vec<u8>,
-        user_id: Uuid,
-    ) -> Result<ColumnMapping> {
-        // Get session
+    /// Step 2: Parse the uploaded file and record its metadata and header fingerprint
+    pub async fn prepare_file(&self, session_id: Uuid, file_data: &[u8]) -> Result<ParsedFile> {
         let session = self.get_session(session_id).await?;
 
-        // Check user quota
-        if !self.claude_service.check_user_quota(user_id).await? {
-            return Err(AppError::QuotaExceeded(
-                "Monthly AI import limit reached. Please upgrade your plan.".to_string()
-            ));
-        }
-
         // Parse file
         tracing::info!("Parsing file for session: {}", session_id);
-        let parsed_file = FileParserService::parse(&file_data, &session.original_filename)?;
+        let parsed_file = FileParserService::parse(file_data, &session.original_filename)?;
 
         tracing::info!(
             "File parsed: {} rows, {} columns, type: {}",
@@ -118,7 +110,7 @@ impl AiImportService {
         );
 
         // Update session with file metadata
-        sqlx::query!(
+        sqlx::query(
             r#"
             UPDATE ai_import_sessions
             SET
@@ -127,20 +119,131 @@ impl AiImportService {
                 file_hash = $3,
                 detected_format = $4,
                 detected_columns = $5,
-                total_rows = $6
-            WHERE id = $7
-            "#,
-            file_data.len() as i64,
-            parsed_file.file_type.to_string(),
-            parsed_file.file_hash,
-            parsed_file.file_type.to_string(),
-            serde_json::to_value(&parsed_file.headers)?,
-            parsed_file.total_rows as i32,
-            session_id
+                total_rows = $6,
+                header_fingerprint = $7
+            WHERE id = $8
+            "#
         )
+        .bind(file_data.len() as i64)
+        .bind(parsed_file.file_type.to_string())
+        .bind(&parsed_file.file_hash)
+        .bind(parsed_file.file_type.to_string())
+        .bind(serde_json::to_value(&parsed_file.headers)?)
+        .bind(parsed_file.total_rows as i32)
+        .bind(header_fingerprint(&parsed_file.headers))
+        .bind(session_id)
         .execute(&self.db_pool)
         .await?;
 
+        Ok(parsed_file)
+    }
+
+    /// Step 3a: Reuse the user's confirmed mapping for this header row, if there is one.
+    /// Returns `None` when the structure is new (or changed) and needs a fresh AI analysis.
+    pub async fn apply_remembered_mapping(
+        &self,
+        session_id: Uuid,
+        user_id: Uuid,
+        parsed_file: &ParsedFile,
+    ) -> Result<Option<ColumnMapping>> {
+        let fingerprint = header_fingerprint(&parsed_file.headers);
+
+        let memory = sqlx::query_as::<_, AiImportMappingMemory>(
+            "SELECT * FROM ai_import_mapping_memory WHERE user_id = $1 AND header_fingerprint = $2"
+        )
+        .bind(user_id)
+        .bind(&fingerprint)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        let Some(memory) = memory else {
+            return Ok(None);
+        };
+
+        let mapping = match serde_json::from_value::<ColumnMapping>(memory.mapping.clone()) {
+            Ok(mapping) if mapping.fits_headers(&parsed_file.headers) => mapping,
+            _ => {
+                tracing::warn!(
+                    "Remembered mapping {} no longer fits session {}, running a fresh analysis",
+                    memory.id, session_id
+                );
+                return Ok(None);
+            }
+        };
+
+        let warning = match &memory.supplier_name {
+            Some(supplier) => format!(
+                "Reused the column mapping confirmed for {} on {}. Review it if the file layout changed.",
+                supplier, memory.confirmed_at.format("%Y-%m-%d")
+            ),
+            None => format!(
+                "Reused the column mapping confirmed on {}. Review it if the file layout changed.",
+                memory.confirmed_at.format("%Y-%m-%d")
+            ),
+        };
+
+        let mut tx = self.db_pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            UPDATE ai_import_sessions
+            SET
+                ai_mapping = $1,
+                ai_confidence_scores = $2,
+                ai_warnings = $3,
+                supplier_name = COALESCE(supplier_name, $4),
+                mapping_source = 'memory',
+                mapping_memory_id = $5,
+                analysis_completed_at = NOW(),
+                status = 'mapping_review'
+            WHERE id = $6
+            "#
+        )
+        .bind(&memory.mapping)
+        .bind(&memory.confidence_scores)
+        .bind(vec![serde_json::json!(warning)])
+        .bind(&memory.supplier_name)
+        .bind(memory.id)
+        .bind(session_id)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            UPDATE ai_import_mapping_memory
+            SET times_reused = times_reused + 1, last_used_at = NOW(), last_session_id = $1
+            WHERE id = $2
+            "#
+        )
+        .bind(session_id)
+        .bind(memory.id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        tracing::info!(
+            "Reused remembered mapping {} for session {} (AI analysis skipped)",
+            memory.id, session_id
+        );
+
+        Ok(Some(mapping))
+    }
+
+    /// Step 3b: Analyze the parsed file with Claude AI
+    pub async fn analyze_file(
+        &self,
+        session_id: Uuid,
+        parsed_file: &ParsedFile,
+        user_id: Uuid,
+    ) -> Result<ColumnMapping> {
+        // Check user quota
+        if !self.claude_service.check_user_quota(user_id).await? {
+            return Err(AppError::QuotaExceeded(
+                "Monthly AI import limit reached. Please upgrade your plan.".to_string()
+            ));
+        }
+
         // Prepare sample data for AI analysis (first 10 rows)
         let sample_rows: Vec<_> = parsed_file.rows.iter().take(10).collect();
         let sample_json = self.format_sample_for_ai(&parsed_file.headers, &sample_rows);
@@ -180,12 +283,14 @@ Return JSON with mapping, confidence scores (0.0-1.0), and any data quality warn
             system_prompt: Some(ANALYSIS_SYSTEM_PROMPT.to_string()),
         };
 
-        let ai_response = self.claude_service.send_message(
+        let validated = self.claude_service.send_message_validated::<serde_json::Value>(
             vec![user_message(analysis_prompt)],
             config,
             user_id,
             Some(session_id),
+            AiFeature::ImportAnalysis,
         ).await?;
+        let ai_response = validated.response;
 
         tracing::info!(
             "AI analysis completed: {} tokens, ${:.4} cost",
@@ -194,7 +299,8 @@ Return JSON with mapping, confidence scores (0.0-1.0), and any data quality warn
         );
 
         // Parse AI response
-        let analysis_result = self.parse_ai_mapping_response(&ai_response.content)?;
+        let analysis_result = self.parse_ai_mapping_response(validated.value)?;
+        self.claude_service.cache_response(&ai_response).await;
 
         // Update session with AI analysis results
         sqlx::query!(
@@ -207,6 +313,7 @@ Return JSON with mapping, confidence scores (0.0-1.0), and any data quality warn
                 ai_api_cost_usd = ai_api_cost_usd + $4,
                 ai_tokens_used = ai_tokens_used + $5,
                 analysis_completed_at = NOW(),
+                mapping_source = 'ai',
                 status = 'mapping_review'
             WHERE id = $6
             "#,
@@ -241,6 +348,313 @@ Return JSON with mapping, confidence scores (0.0-1.0), and any data quality warn
         Ok(session)
     }
 
+    /// Remember the mapping a user confirmed for this session's header row,
+    /// replacing whatever was remembered for the same structure before
+    pub async fn remember_mapping(&self, session: &AiImportSession, mapping: &ColumnMapping) -> Result<()> {
+        let Some(fingerprint) = &session.header_fingerprint else {
+            return Ok(());
+        };
+
+        sqlx::query(
+            r#"
+            INSERT INTO ai_import_mapping_memory (
+                user_id, header_fingerprint, headers, supplier_name,
+                mapping, confidence_scores, last_session_id, confirmed_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, NOW())
+            ON CONFLICT (user_id, header_fingerprint) DO UPDATE SET
+                headers = EXCLUDED.headers,
+                supplier_name = COALESCE(EXCLUDED.supplier_name, ai_import_mapping_memory.supplier_name),
+                mapping = EXCLUDED.mapping,
+                confidence_scores = EXCLUDED.confidence_scores,
+                last_session_id = EXCLUDED.last_session_id,
+                confirmed_at = NOW()
+            "#
+        )
+        .bind(session.user_id)
+        .bind(fingerprint)
+        .bind(session.detected_columns.clone().unwrap_or_else(|| serde_json::json!([])))
+        .bind(&session.supplier_name)
+        .bind(serde_json::to_value(mapping)?)
+        .bind(&session.ai_confidence_scores)
+        .bind(session.id)
+        .execute(&self.db_pool)
+        .await?;
+
+        tracing::info!("Remembered confirmed column mapping for session: {}", session.id);
+
+        Ok(())
+    }
+
+    /// Remembered mappings of a user, most recently confirmed first
+    pub async fn list_mapping_memory(&self, user_id: Uuid) -> Result<Vec<AiImportMappingMemory>> {
+        let memories = sqlx::query_as::<_, AiImportMappingMemory>(
+            "SELECT * FROM ai_import_mapping_memory WHERE user_id = $1 ORDER BY confirmed_at DESC"
+        )
+        .bind(user_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(memories)
+    }
+
+    /// Forget a remembered mapping so the next upload with that header row is analyzed afresh
+    pub async fn delete_mapping_memory(&self, user_id: Uuid, memory_id: Uuid) -> Result<()> {
+        let result = sqlx::query("DELETE FROM ai_import_mapping_memory WHERE id = $1 AND user_id = $2")
+            .bind(memory_id)
+            .bind(user_id)
+            .execute(&self.db_pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Remembered mapping not found".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Record accept/reject decisions for rows of a session awaiting mapping review
+    pub async fn set_row_decisions(
+        &self,
+        session: &AiImportSession,
+        decided_by: Uuid,
+        decisions: &[RowDecisionInput],
+    ) -> Result<Vec<AiImportRowDecision>> {
+        if session.status != ImportStatus::MappingReview.to_string() {
+            return Err(AppError::BadRequest(format!(
+                "Rows can only be reviewed before import. Current state: {}",
+                session.status
+            )));
+        }
+
+        let total_rows = session.total_rows.unwrap_or(0);
+        if let Some(input) = decisions.iter().find(|d| d.row_number > total_rows) {
+            return Err(AppError::InvalidInput(format!(
+                "Row {} is out of range (file has {} rows)",
+                input.row_number, total_rows
+            )));
+        }
+
+        let mut tx = self.db_pool.begin().await?;
+
+        for input in decisions {
+            sqlx::query(
+                r#"
+                INSERT INTO ai_import_row_decisions (session_id, row_number, decision, reason, decided_by)
+                VALUES ($1, $2, $3, $4, $5)
+                ON CONFLICT (session_id, row_number) DO UPDATE SET
+                    decision = EXCLUDED.decision,
+                    reason = EXCLUDED.reason,
+                    decided_by = EXCLUDED.decided_by,
+                    decided_at = NOW()
+                "#
+            )
+            .bind(session.id)
+            .bind(input.row_number)
+            .bind(input.decision.as_str())
+            .bind(&input.reason)
+            .bind(decided_by)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        self.get_row_decisions(session.id).await
+    }
+
+    /// Row decisions of a session, in row order
+    pub async fn get_row_decisions(&self, session_id: Uuid) -> Result<Vec<AiImportRowDecision>> {
+        let decisions = sqlx::query_as::<_, AiImportRowDecision>(
+            "SELECT * FROM ai_import_row_decisions WHERE session_id = $1 ORDER BY row_number"
+        )
+        .bind(session_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(decisions)
+    }
+
+    /// 1-based numbers of the rows rejected during review
+    pub async fn rejected_rows(&self, session_id: Uuid) -> Result<HashSet<usize>> {
+        let rows = sqlx::query_scalar::<_, i32>(
+            "SELECT row_number FROM ai_import_row_decisions WHERE session_id = $1 AND decision = $2"
+        )
+        .bind(session_id)
+        .bind(RowDecision::Reject.as_str())
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|r| r as usize).collect())
+    }
+
+    /// Rows as they would be imported with the session's mapping, with their review decisions
+    pub async fn preview_rows(
+        &self,
+        session: &AiImportSession,
+        parsed_file: &ParsedFile,
+        mapping: &ColumnMapping,
+        offset: usize,
+        limit: usize,
+    ) -> Result<RowPreviewResponse> {
+        let decisions: HashMap<usize, AiImportRowDecision> = self.get_row_decisions(session.id).await?
+            .into_iter()
+            .map(|d| (d.row_number as usize, d))
+            .collect();
+        let rows_rejected = decisions.values()
+            .filter(|d| d.decision == RowDecision::Reject.as_str())
+            .count();
+
+        let validator = InventoryValidatorService::new(self.db_pool.clone());
+        let mut rows = Vec::new();
+        for (idx, row_data) in parsed_file.rows.iter().enumerate().skip(offset).take(limit) {
+            let row_number = idx + 1;
+            let mapped = validator.map_row_to_inventory(row_number, &parsed_file.headers, row_data, mapping)?;
+            let decision = decisions.get(&row_number);
+
+            rows.push(RowPreview {
+                row_number,
+                mapped,
+                decision: decision.map(|d| d.decision.clone()),
+                reason: decision.and_then(|d| d.reason.clone()),
+            });
+        }
+
+        Ok(RowPreviewResponse {
+            session_id: session.id,
+            total_rows: parsed_file.rows.len(),
+            rows_rejected,
+            rows,
+        })
+    }
+
+    /// Reverse the stock a completed import created. Each imported inventory row is set to
+    /// zero with the reversal recorded in `inventory_audit`; rows whose stock changed after
+    /// the import (sold, adjusted, or under inquiry) are skipped and reported.
+    pub async fn rollback_import(&self, session: &AiImportSession, user_id: Uuid) -> Result<RollbackResult> {
+        if session.status != ImportStatus::Completed.to_string() {
+            return Err(AppError::BadRequest(format!(
+                "Only completed imports can be rolled back. Current state: {}",
+                session.status
+            )));
+        }
+
+        let mut tx = self.db_pool.begin().await?;
+
+        let imported: Vec<(Uuid, i32, Uuid, Option<serde_json::Value>)> = sqlx::query_as(
+            r#"
+            SELECT id, row_number, created_inventory_id, mapped_data
+            FROM ai_import_row_results
+            WHERE session_id = $1 AND created_inventory_id IS NOT NULL AND rolled_back_at IS NULL
+            ORDER BY row_number
+            "#
+        )
+        .bind(session.id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut rows_rolled_back = 0u32;
+        let mut quantity_reversed = 0i64;
+        let mut skipped = Vec::new();
+
+        for (row_id, row_number, inventory_id, mapped_data) in imported {
+            let imported_quantity = mapped_data
+                .and_then(|m| m.get("quantity").and_then(|q| q.as_i64()))
+                .unwrap_or(0) as i32;
+
+            let current: Option<(i32, Option<String>, bool)> = sqlx::query_as(
+                r#"
+                SELECT i.quantity, i.status,
+                       EXISTS(SELECT 1 FROM inquiries q WHERE q.inventory_id = i.id)
+                FROM inventory i
+                WHERE i.id = $1 AND i.user_id = $2
+                FOR UPDATE OF i
+                "#
+            )
+            .bind(inventory_id)
+            .bind(session.user_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+            let Some((current_quantity, status, has_inquiries)) = current else {
+                skipped.push(RollbackSkippedRow {
+                    row_number,
+                    inventory_id,
+                    reason: "Inventory row no longer exists".to_string(),
+                });
+                continue;
+            };
+
+            if let Some(reason) = rollback_blocker(imported_quantity, current_quantity, has_inquiries) {
+                skipped.push(RollbackSkippedRow { row_number, inventory_id, reason });
+                continue;
+            }
+
+            sqlx::query("UPDATE inventory SET quantity = 0, updated_at = NOW() WHERE id = $1")
+                .bind(inventory_id)
+                .execute(&mut *tx)
+                .await?;
+
+            sqlx::query(
+                r#"
+                INSERT INTO inventory_audit (
+                    inventory_id, user_id, action, old_quantity, new_quantity,
+                    old_status, new_status, notes
+                ) VALUES ($1, $2, 'import_rollback', $3, 0, $4, $4, $5)
+                "#
+            )
+            .bind(inventory_id)
+            .bind(user_id)
+            .bind(current_quantity)
+            .bind(&status)
+            .bind(format!("Rolled back AI import session {} (row {})", session.id, row_number))
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query(
+                "UPDATE ai_import_row_results SET status = 'rolled_back', rolled_back_at = NOW() WHERE id = $1"
+            )
+            .bind(row_id)
+            .execute(&mut *tx)
+            .await?;
+
+            rows_rolled_back += 1;
+            quantity_reversed += current_quantity as i64;
+        }
+
+        // The session only counts as rolled back once every imported row was reversed
+        let status = if skipped.is_empty() { ImportStatus::RolledBack } else { ImportStatus::Completed };
+
+        sqlx::query(
+            r#"
+            UPDATE ai_import_sessions
+            SET rows_rolled_back = COALESCE(rows_rolled_back, 0) + $1,
+                rolled_back_at = NOW(),
+                status = $2
+            WHERE id = $3
+            "#
+        )
+        .bind(rows_rolled_back as i32)
+        .bind(status.to_string())
+        .bind(session.id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        tracing::info!(
+            "Rolled back import session {}: {} rows reversed, {} skipped",
+            session.id, rows_rolled_back, skipped.len()
+        );
+
+        Ok(RollbackResult {
+            session_id: session.id,
+            rows_rolled_back,
+            quantity_reversed,
+            skipped,
+            status,
+        })
+    }
+
     /// Format sample data for AI analysis
     fn format_sample_for_ai(&self, headers: &[String], rows: &[&Vec<String>]) -> String {
         let mut output = String::new();
@@ -257,21 +671,8 @@ Return JSON with mapping, confidence scores (0.0-1.0), and any data quality warn
         output
     }
 
-    /// Parse Claude's JSON response into structured mapping
-    fn parse_ai_mapping_response(&self, content: &str) -> Result<AnalysisResult> {
-        // Extract JSON from response (Claude sometimes adds text before/after)
-        let json_start = content.find('{').ok_or_else(|| {
-            AppError::Internal(anyhow::anyhow!("AI response missing JSON object"))
-        })?;
-        let json_end = content.rfind('}').ok_or_else(|| {
-            AppError::Internal(anyhow::anyhow!("AI response missing JSON closing brace"))
-        })?;
-
-        let json_str = &content[json_start..=json_end];
-
-        let parsed: serde_json::Value = serde_json::from_str(json_str)
-            .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to parse AI JSON: {}", e)))?;
-
+    /// Turn Claude's validated JSON into a structured mapping
+    fn parse_ai_mapping_response(&self, parsed: serde_json::Value) -> Result<AnalysisResult> {
         let mapping: ColumnMapping = serde_json::from_value(
             parsed.get("mapping").cloned().unwrap_or(serde_json::json!({}))
         )?;
@@ -0,0 +1,428 @@
+/// Extraction and schema validation of structured AI output
+///
+/// Claude is asked for JSON but may wrap it in markdown fences or prose, or drift from
+/// the requested shape (a missing field, a number sent as a string). Output is recovered
+/// with `extract_json`, checked against the feature's schema and only then deserialized.
+/// Schemas use a subset of JSON Schema: `type`, `properties`, `required`,
+/// `additionalProperties`, `items`, `enum`, `minimum`/`maximum`, `minLength` and `anyOf`.
+///
+/// `ClaudeAIService::send_message_validated` runs this check and, when it fails, asks the
+/// model once to repair its answer with the validation errors fed back.
+
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use crate::services::ai_response_cache_service::AiFeature;
+
+/// Errors reported back to the model and the client are capped at this many
+const MAX_REPORTED_ERRORS: usize = 10;
+
+/// AI output that failed validation, after the repair attempt
+#[derive(Debug, Clone)]
+pub struct AiOutputError {
+    pub feature: AiFeature,
+    pub errors: Vec<String>,
+}
+
+impl std::fmt::Display for AiOutputError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} output failed validation: {}", self.feature.as_str(), self.errors.join("; "))
+    }
+}
+
+/// The JSON document in a model response: the body of a ```json fence, or the outermost
+/// object/array when the model added prose around it
+pub fn extract_json(content: &str) -> Option<&str> {
+    let content = content.trim();
+
+    if let Some(fence_start) = content.find("```") {
+        let body = &content[fence_start + 3..];
+        // Skip the language tag on the opening fence
+        let body = body.find('\n').map(|i| &body[i + 1..]).unwrap_or(body);
+        let body = body.find("```").map(|i| &body[..i]).unwrap_or(body).trim();
+        if body.starts_with('{') || body.starts_with('[') {
+            return Some(body);
+        }
+    }
+
+    let start = content.find(|c| c == '{' || c == '[')?;
+    let close = if content[start..].starts_with('{') { '}' } else { ']' };
+    let end = content.rfind(close)?;
+    (end > start).then(|| &content[start..=end])
+}
+
+/// Validate `instance` against `schema`, returning one message per violation
+pub fn validate_schema(schema: &Value, instance: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    validate_at(schema, instance, "$", &mut errors);
+    errors
+}
+
+fn validate_at(schema: &Value, instance: &Value, path: &str, errors: &mut Vec<String>) {
+    if let Some(options) = schema.get("anyOf").and_then(Value::as_array) {
+        // Report the errors of the closest option
+        let mut closest: Option<Vec<String>> = None;
+        for option in options {
+            let mut option_errors = Vec::new();
+            validate_at(option, instance, path, &mut option_errors);
+            if option_errors.is_empty() {
+                closest = None;
+                break;
+            }
+            if closest.as_ref().map_or(true, |c| option_errors.len() < c.len()) {
+                closest = Some(option_errors);
+            }
+        }
+        errors.extend(closest.unwrap_or_default());
+    }
+
+    let types: Vec<&str> = match schema.get("type") {
+        Some(Value::String(t)) => vec![t.as_str()],
+        Some(Value::Array(ts)) => ts.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+    if !types.is_empty() && !types.iter().any(|t| type_matches(t, instance)) {
+        errors.push(format!("{}: expected {}, got {}", path, types.join(" or "), type_name(instance)));
+        return;
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.contains(instance) {
+            errors.push(format!("{}: must be one of {}", path, Value::Array(allowed.clone())));
+        }
+    }
+
+    if let Some(n) = instance.as_f64() {
+        if let Some(min) = schema.get("minimum").and_then(Value::as_f64) {
+            if n < min {
+                errors.push(format!("{}: {} is below the minimum of {}", path, n, min));
+            }
+        }
+        if let Some(max) = schema.get("maximum").and_then(Value::as_f64) {
+            if n > max {
+                errors.push(format!("{}: {} is above the maximum of {}", path, n, max));
+            }
+        }
+    }
+
+    if let (Some(s), Some(min)) = (instance.as_str(), schema.get("minLength").and_then(Value::as_u64)) {
+        if (s.trim().chars().count() as u64) < min {
+            errors.push(format!("{}: must not be empty", path));
+        }
+    }
+
+    if let Some(object) = instance.as_object() {
+        if let Some(required) = schema.get("required").and_then(Value::as_array) {
+            for key in required.iter().filter_map(Value::as_str) {
+                if !object.contains_key(key) {
+                    errors.push(format!("{}: missing required field '{}'", path, key));
+                }
+            }
+        }
+
+        let properties = schema.get("properties").and_then(Value::as_object);
+        for (key, value) in object {
+            let field_path = format!("{}.{}", path, key);
+            match properties.and_then(|p| p.get(key)) {
+                Some(field_schema) => validate_at(field_schema, value, &field_path, errors),
+                None => match schema.get("additionalProperties") {
+                    Some(Value::Bool(false)) => errors.push(format!("{}: unexpected field", field_path)),
+                    Some(extra) if extra.is_object() => validate_at(extra, value, &field_path, errors),
+                    _ => {}
+                },
+            }
+        }
+    }
+
+    if let (Some(items), Some(item_schema)) = (instance.as_array(), schema.get("items")) {
+        for (i, item) in items.iter().enumerate() {
+            validate_at(item_schema, item, &format!("{}[{}]", path, i), errors);
+        }
+    }
+}
+
+fn type_matches(expected: &str, instance: &Value) -> bool {
+    match expected {
+        "object" => instance.is_object(),
+        "array" => instance.is_array(),
+        "string" => instance.is_string(),
+        "boolean" => instance.is_boolean(),
+        "null" => instance.is_null(),
+        "number" => instance.is_number(),
+        "integer" => instance.is_i64() || instance.is_u64() || instance.as_f64().map_or(false, |n| n.fract() == 0.0),
+        _ => true,
+    }
+}
+
+fn type_name(instance: &Value) -> &'static str {
+    match instance {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Extract, validate and deserialize a model response
+pub fn parse_validated<T: DeserializeOwned>(feature: AiFeature, content: &str) -> std::result::Result<T, Vec<String>> {
+    let json = extract_json(content).ok_or_else(|| vec!["$: response contains no JSON object".to_string()])?;
+    let value: Value = serde_json::from_str(json).map_err(|e| vec![format!("$: invalid JSON: {}", e)])?;
+
+    let mut errors = validate_schema(&output_schema(feature), &value);
+    if !errors.is_empty() {
+        errors.truncate(MAX_REPORTED_ERRORS);
+        return Err(errors);
+    }
+
+    serde_json::from_value(value).map_err(|e| vec![format!("$: {}", e)])
+}
+
+/// Follow-up message asking the model to fix its previous answer
+pub fn repair_prompt(errors: &[String]) -> String {
+    format!(
+        "Your previous response could not be used because it does not match the required JSON format:\n{}\n\n\
+         Return the complete corrected JSON only, with no markdown fences or commentary.",
+        errors.iter().map(|e| format!("- {}", e)).collect::<Vec<_>>().join("\n")
+    )
+}
+
+fn confidence() -> Value {
+    json!({ "type": "number", "minimum": 0, "maximum": 1 })
+}
+
+fn extracted_fields() -> Value {
+    json!({
+        "type": "object",
+        "additionalProperties": {
+            "type": "object",
+            "required": ["confidence"],
+            "properties": {
+                "value": { "type": ["string", "null"] },
+                "confidence": { "type": "number" }
+            }
+        }
+    })
+}
+
+/// Expected shape of each feature's output, matching its system prompt
+pub fn output_schema(feature: AiFeature) -> Value {
+    let nullable_string = json!({ "type": ["string", "null"] });
+    let strings = json!({ "type": "array", "items": { "type": "string" } });
+
+    match feature {
+        AiFeature::ImportAnalysis => json!({
+            "type": "object",
+            "required": ["mapping"],
+            "properties": {
+                "mapping": { "type": "object", "additionalProperties": nullable_string },
+                "confidence_scores": { "type": "object", "additionalProperties": { "type": ["number", "null"] } },
+                "warnings": strings
+            }
+        }),
+        AiFeature::DocumentExtraction => json!({
+            "type": "object",
+            "required": ["document_kind"],
+            "properties": {
+                "document_kind": { "type": "string", "enum": ["coa", "price_list", "packing_list"] },
+                "header": extracted_fields(),
+                "line_items": { "type": "array", "items": extracted_fields() },
+                "warnings": strings
+            }
+        }),
+        AiFeature::NlQuery => json!({
+            "anyOf": [
+                {
+                    "type": "object",
+                    "required": ["type", "answer"],
+                    "properties": {
+                        "type": { "enum": ["conversation"] },
+                        "answer": { "type": "string", "minLength": 1 },
+                        "follow_up_suggestions": strings
+                    }
+                },
+                {
+                    "type": "object",
+                    "required": ["type", "sql"],
+                    "properties": {
+                        "type": { "enum": ["sql_query"] },
+                        "sql": { "type": "string", "minLength": 1 },
+                        "explanation": nullable_string,
+                        "parameters": { "type": ["array", "null"] }
+                    }
+                }
+            ]
+        }),
+        AiFeature::InquiryAssistant => json!({
+            "type": "object",
+            "required": ["response"],
+            "properties": {
+                "response": { "type": "string", "minLength": 1 },
+                "english_translation": nullable_string,
+                "reasoning": nullable_string,
+                "negotiation_tips": strings
+            }
+        }),
+        AiFeature::PricingSuggestion => json!({
+            "type": "object",
+            "required": ["suggested_unit_price", "confidence", "rationale"],
+            "properties": {
+                "suggested_unit_price": { "type": "number", "minimum": 0 },
+                "price_range_low": { "type": ["number", "null"], "minimum": 0 },
+                "price_range_high": { "type": ["number", "null"], "minimum": 0 },
+                "confidence": confidence(),
+                "rationale": { "type": "string", "minLength": 1 }
+            }
+        }),
+        AiFeature::RegulatoryDocument => json!({
+            "type": "object",
+            "required": ["header"],
+            "properties": {
+                "header": { "type": "object" },
+                "citations": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["ref"],
+                        "properties": {
+                            "ref": { "type": "string", "minLength": 1 },
+                            "applies_to": nullable_string
+                        }
+                    }
+                }
+            }
+        }),
+        AiFeature::ErpMappingDiscovery => json!({
+            "type": "object",
+            "required": ["mappings", "unmapped_atlas_items", "unmapped_erp_items", "warnings"],
+            "properties": {
+                "mappings": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["atlas_inventory_id", "erp_item_id", "erp_item_name", "confidence_score", "matching_factors", "reasoning"],
+                        "properties": {
+                            "atlas_inventory_id": { "type": "string" },
+                            "erp_item_id": { "type": "string" },
+                            "erp_item_name": { "type": "string" },
+                            "erp_item_description": nullable_string,
+                            "confidence_score": confidence(),
+                            "matching_factors": { "type": "object" },
+                            "reasoning": { "type": "string" }
+                        }
+                    }
+                },
+                "unmapped_atlas_items": strings,
+                "unmapped_erp_items": strings,
+                "warnings": strings
+            }
+        }),
+        AiFeature::ErpSyncAnalysis => json!({
+            "type": "object",
+            "required": ["insight_type", "severity", "title", "explanation", "recommendations", "actionable"],
+            "properties": {
+                "insight_type": {
+                    "type": "string",
+                    "enum": ["error_explanation", "performance_analysis", "data_quality", "anomaly_detection", "success_summary"]
+                },
+                "severity": { "type": "string", "enum": ["info", "warning", "error", "critical"] },
+                "title": { "type": "string", "minLength": 1 },
+                "explanation": { "type": "string" },
+                "recommendations": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["action", "priority", "description"],
+                        "properties": {
+                            "action": { "type": "string" },
+                            "priority": { "type": "string", "enum": ["high", "medium", "low"] },
+                            "description": { "type": "string" }
+                        }
+                    }
+                },
+                "actionable": { "type": "boolean" }
+            }
+        }),
+        AiFeature::ErpConflictResolution => json!({
+            "type": "object",
+            "required": ["resolutions"],
+            "properties": {
+                "resolutions": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["conflict_type", "suggested_resolution", "confidence_score", "risk_level", "reasoning", "evidence"],
+                        "properties": {
+                            "conflict_type": { "type": "string" },
+                            "suggested_resolution": {
+                                "type": "string",
+                                "enum": ["atlas_wins", "erp_wins", "manual_review", "merge", "reject_sync"]
+                            },
+                            "confidence_score": confidence(),
+                            "risk_level": { "type": "string", "enum": ["low", "medium", "high", "critical"] },
+                            "reasoning": { "type": "string" },
+                            "evidence": { "type": "object" }
+                        }
+                    }
+                }
+            }
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_json_from_fences_and_prose() {
+        assert_eq!(extract_json("```json\n{\"a\": 1}\n```"), Some("{\"a\": 1}"));
+        assert_eq!(extract_json("```\n[1, 2]\n```"), Some("[1, 2]"));
+        assert_eq!(extract_json("Here is the result:\n{\"a\": {\"b\": 2}}\nThanks"), Some("{\"a\": {\"b\": 2}}"));
+        assert_eq!(extract_json("{\"a\": 1}"), Some("{\"a\": 1}"));
+        assert_eq!(extract_json("no json here"), None);
+    }
+
+    #[test]
+    fn test_schema_reports_paths() {
+        let schema = output_schema(AiFeature::ErpSyncAnalysis);
+        let instance = json!({
+            "insight_type": "data_quality",
+            "severity": "urgent",
+            "title": "Sync failed",
+            "explanation": "…",
+            "recommendations": [{ "action": "Retry", "priority": 1, "description": "…" }]
+        });
+
+        let errors = validate_schema(&schema, &instance);
+        assert!(errors.iter().any(|e| e.starts_with("$: missing required field 'actionable'")));
+        assert!(errors.iter().any(|e| e.starts_with("$.severity: must be one of")));
+        assert!(errors.iter().any(|e| e == "$.recommendations[0].priority: expected string, got number"));
+    }
+
+    #[test]
+    fn test_any_of_accepts_either_shape() {
+        let schema = output_schema(AiFeature::NlQuery);
+        assert!(validate_schema(&schema, &json!({ "type": "conversation", "answer": "Hi" })).is_empty());
+        assert!(validate_schema(&schema, &json!({ "type": "sql_query", "sql": "SELECT 1" })).is_empty());
+        assert_eq!(
+            validate_schema(&schema, &json!({ "type": "sql_query" })),
+            vec!["$: missing required field 'sql'".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_parse_validated_pricing() {
+        #[derive(serde::Deserialize)]
+        struct Pricing {
+            suggested_unit_price: f64,
+        }
+
+        let ok = "```json\n{\"suggested_unit_price\": 12.5, \"confidence\": 0.7, \"rationale\": \"Comparable listings\"}\n```";
+        assert_eq!(parse_validated::<Pricing>(AiFeature::PricingSuggestion, ok).unwrap().suggested_unit_price, 12.5);
+
+        let drifted = "{\"suggested_unit_price\": \"$12.50\", \"confidence\": 1.4, \"rationale\": \"\"}";
+        let errors = parse_validated::<Pricing>(AiFeature::PricingSuggestion, drifted).err().unwrap();
+        assert_eq!(errors.len(), 3);
+    }
+}
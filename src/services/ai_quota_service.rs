@@ -0,0 +1,382 @@
+/// AI quota tiers, admin overrides, top-ups and the monthly reset
+///
+/// Each user's effective limit per feature (tier limit, replaced by an admin override,
+/// plus top-ups bought this period) is written to `user_ai_usage_limits`, which every AI
+/// service already checks before calling Claude.
+
+use std::collections::HashMap;
+use std::time::Duration;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::{
+    middleware::error_handling::{Result, AppError},
+    models::ai_quota::*,
+    services::RuntimeSettings,
+};
+
+pub struct AiQuotaService {
+    db_pool: PgPool,
+}
+
+impl AiQuotaService {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self { db_pool }
+    }
+
+    pub async fn list_tiers(&self) -> Result<Vec<AiQuotaTier>> {
+        let tiers = sqlx::query_as::<_, AiQuotaTier>(
+            "SELECT * FROM ai_quota_tiers ORDER BY monthly_price_usd, tier"
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(tiers)
+    }
+
+    async fn get_tier(&self, tier: &str) -> Result<AiQuotaTier> {
+        sqlx::query_as::<_, AiQuotaTier>("SELECT * FROM ai_quota_tiers WHERE tier = $1")
+            .bind(tier)
+            .fetch_optional(&self.db_pool)
+            .await?
+            .ok_or_else(|| AppError::BadRequest(format!("Unknown AI plan tier '{}'", tier)))
+    }
+
+    async fn ensure_limits_row(&self, user_id: Uuid) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO user_ai_usage_limits (user_id) VALUES ($1) ON CONFLICT (user_id) DO NOTHING"
+        )
+        .bind(user_id)
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_usage_row(&self, user_id: Uuid) -> Result<UserAiUsageRow> {
+        self.ensure_limits_row(user_id).await?;
+
+        let row = sqlx::query_as::<_, UserAiUsageRow>(
+            r#"
+            SELECT
+                plan_tier, limit_period_start, limit_period_end,
+                monthly_import_limit, monthly_imports_used,
+                monthly_nl_query_limit, monthly_nl_queries_used,
+                monthly_inquiry_assist_limit, monthly_inquiry_assists_used,
+                monthly_pricing_suggestion_limit, monthly_pricing_suggestions_used,
+                monthly_erp_ai_mapping_limit, monthly_erp_ai_mapping_used,
+                monthly_erp_ai_analysis_limit, monthly_erp_ai_analysis_used,
+                monthly_erp_ai_conflict_limit, monthly_erp_ai_conflict_used,
+                monthly_ai_cost_limit_usd, monthly_ai_cost_used_usd
+            FROM user_ai_usage_limits
+            WHERE user_id = $1
+            "#
+        )
+        .bind(user_id)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    /// Active overrides by feature
+    async fn get_overrides(&self, user_id: Uuid) -> Result<HashMap<String, Decimal>> {
+        let rows: Vec<(String, Decimal)> = sqlx::query_as(
+            r#"
+            SELECT feature, monthly_limit
+            FROM ai_quota_overrides
+            WHERE user_id = $1 AND (expires_at IS NULL OR expires_at > NOW())
+            "#
+        )
+        .bind(user_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(rows.into_iter().collect())
+    }
+
+    /// Completed top-ups of the current period by feature
+    async fn get_topups(&self, user_id: Uuid, period_end: chrono::NaiveDate) -> Result<HashMap<String, Decimal>> {
+        let rows: Vec<(String, Decimal)> = sqlx::query_as(
+            r#"
+            SELECT feature, SUM(amount)
+            FROM ai_quota_topups
+            WHERE user_id = $1 AND period_end = $2 AND status = 'completed'
+            GROUP BY feature
+            "#
+        )
+        .bind(user_id)
+        .bind(period_end)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(rows.into_iter().collect())
+    }
+
+    /// Recompute and store a user's effective limits
+    pub async fn sync_limits(&self, user_id: Uuid) -> Result<()> {
+        let row = self.get_usage_row(user_id).await?;
+        let tier = self.get_tier(&row.plan_tier).await?;
+        let overrides = self.get_overrides(user_id).await?;
+        let topups = self.get_topups(user_id, row.limit_period_end).await?;
+
+        // Column names come from QuotaFeature, never from input
+        let assignments = QuotaFeature::ALL
+            .iter()
+            .enumerate()
+            .map(|(i, f)| format!("{} = ${}", f.limit_column(), i + 2))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let sql = format!(
+            "UPDATE user_ai_usage_limits SET {}, updated_at = NOW() WHERE user_id = $1",
+            assignments
+        );
+
+        let mut query = sqlx::query(&sql).bind(user_id);
+        for feature in QuotaFeature::ALL {
+            let limit = effective_limit(
+                tier.limit(feature),
+                overrides.get(feature.as_str()).copied(),
+                topups.get(feature.as_str()).copied().unwrap_or_default(),
+            );
+            query = if feature.is_cost() {
+                query.bind(limit.round_dp(2))
+            } else {
+                query.bind(limit.trunc().to_i32().unwrap_or(i32::MAX))
+            };
+        }
+        query.execute(&self.db_pool).await?;
+
+        Ok(())
+    }
+
+    /// Per-feature limits, usage and where each limit comes from
+    pub async fn get_usage(&self, user_id: Uuid) -> Result<AiQuotaUsage> {
+        let row = self.get_usage_row(user_id).await?;
+        let tier = self.get_tier(&row.plan_tier).await?;
+        let overrides = self.get_overrides(user_id).await?;
+        let topups = self.get_topups(user_id, row.limit_period_end).await?;
+
+        let features = QuotaFeature::ALL
+            .into_iter()
+            .map(|feature| {
+                let (limit, used) = row.usage(feature);
+                FeatureUsage {
+                    feature,
+                    limit: to_f64(limit),
+                    used: to_f64(used),
+                    remaining: to_f64((limit - used).max(Decimal::ZERO)),
+                    tier_limit: to_f64(tier.limit(feature)),
+                    override_limit: overrides.get(feature.as_str()).copied().map(to_f64),
+                    topup: to_f64(topups.get(feature.as_str()).copied().unwrap_or_default()),
+                }
+            })
+            .collect();
+
+        Ok(AiQuotaUsage {
+            plan_tier: row.plan_tier,
+            period_start: row.limit_period_start,
+            period_end: row.limit_period_end,
+            features,
+        })
+    }
+
+    /// Move a user to another plan tier
+    pub async fn set_tier(&self, user_id: Uuid, tier: &str) -> Result<AiQuotaUsage> {
+        self.get_tier(tier).await?;
+        self.ensure_limits_row(user_id).await?;
+
+        sqlx::query("UPDATE user_ai_usage_limits SET plan_tier = $1, updated_at = NOW() WHERE user_id = $2")
+            .bind(tier)
+            .bind(user_id)
+            .execute(&self.db_pool)
+            .await?;
+
+        self.sync_limits(user_id).await?;
+        self.get_usage(user_id).await
+    }
+
+    pub async fn set_override(
+        &self,
+        user_id: Uuid,
+        feature: QuotaFeature,
+        request: &SetQuotaOverrideRequest,
+        set_by: Uuid,
+    ) -> Result<AiQuotaOverride> {
+        if request.monthly_limit.is_sign_negative() {
+            return Err(AppError::BadRequest("monthly_limit must not be negative".to_string()));
+        }
+
+        let record = sqlx::query_as::<_, AiQuotaOverride>(
+            r#"
+            INSERT INTO ai_quota_overrides (user_id, feature, monthly_limit, reason, set_by, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (user_id, feature) DO UPDATE SET
+                monthly_limit = EXCLUDED.monthly_limit,
+                reason = EXCLUDED.reason,
+                set_by = EXCLUDED.set_by,
+                expires_at = EXCLUDED.expires_at,
+                created_at = NOW()
+            RETURNING *
+            "#
+        )
+        .bind(user_id)
+        .bind(feature.as_str())
+        .bind(request.monthly_limit)
+        .bind(&request.reason)
+        .bind(set_by)
+        .bind(request.expires_at)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        self.sync_limits(user_id).await?;
+        Ok(record)
+    }
+
+    pub async fn remove_override(&self, user_id: Uuid, feature: QuotaFeature) -> Result<()> {
+        let result = sqlx::query("DELETE FROM ai_quota_overrides WHERE user_id = $1 AND feature = $2")
+            .bind(user_id)
+            .bind(feature.as_str())
+            .execute(&self.db_pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound(format!("No {} override for this user", feature)));
+        }
+
+        self.sync_limits(user_id).await
+    }
+
+    /// Buy extra quota for the current period.
+    ///
+    /// Payment capture is not integrated yet: the top-up is recorded as completed with a
+    /// stub payment reference and credited immediately.
+    pub async fn purchase_topup(&self, user_id: Uuid, request: &PurchaseTopupRequest) -> Result<PurchaseTopupResponse> {
+        let amount = if request.feature.is_cost() { request.amount.round_dp(2) } else { request.amount.trunc() };
+        // Largest single top-up, in requests or dollars (runtime setting)
+        let max_amount = RuntimeSettings::ai_max_topup_amount();
+        if amount <= Decimal::ZERO || amount > Decimal::from(max_amount) {
+            return Err(AppError::BadRequest(format!(
+                "Top-up amount must be between 1 and {}",
+                max_amount
+            )));
+        }
+
+        let row = self.get_usage_row(user_id).await?;
+        let price = (amount * request.feature.topup_unit_price_usd()).round_dp(2);
+
+        let topup = sqlx::query_as::<_, AiQuotaTopup>(
+            r#"
+            INSERT INTO ai_quota_topups (user_id, feature, amount, price_usd, status, payment_reference, period_end)
+            VALUES ($1, $2, $3, $4, 'completed', $5, $6)
+            RETURNING *
+            "#
+        )
+        .bind(user_id)
+        .bind(request.feature.as_str())
+        .bind(amount)
+        .bind(price)
+        .bind(format!("stub-{}", Uuid::new_v4()))
+        .bind(row.limit_period_end)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        self.sync_limits(user_id).await?;
+
+        tracing::info!(
+            "AI quota top-up: user={}, feature={}, amount={}, price=${}",
+            user_id,
+            request.feature,
+            amount,
+            price
+        );
+
+        Ok(PurchaseTopupResponse {
+            topup,
+            usage: self.get_usage(user_id).await?,
+        })
+    }
+
+    pub async fn list_topups(&self, user_id: Uuid) -> Result<Vec<AiQuotaTopup>> {
+        let topups = sqlx::query_as::<_, AiQuotaTopup>(
+            "SELECT * FROM ai_quota_topups WHERE user_id = $1 ORDER BY created_at DESC LIMIT 100"
+        )
+        .bind(user_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(topups)
+    }
+
+    /// Start a new period for every user whose period ended: counters go to zero, and
+    /// last period's top-ups and expired overrides drop out of the limits
+    pub async fn reset_expired_periods(&self) -> Result<usize> {
+        let used_resets = QuotaFeature::ALL
+            .iter()
+            .map(|f| format!("{} = 0", f.used_column()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let sql = format!(
+            r#"
+            UPDATE user_ai_usage_limits
+            SET {},
+                limit_period_start = CURRENT_DATE,
+                limit_period_end = CURRENT_DATE + INTERVAL '1 month',
+                updated_at = NOW()
+            WHERE limit_period_end <= CURRENT_DATE
+            RETURNING user_id
+            "#,
+            used_resets
+        );
+
+        let user_ids: Vec<Uuid> = sqlx::query_scalar(&sql).fetch_all(&self.db_pool).await?;
+
+        sqlx::query("DELETE FROM ai_quota_overrides WHERE expires_at <= NOW()")
+            .execute(&self.db_pool)
+            .await?;
+
+        for user_id in &user_ids {
+            if let Err(e) = self.sync_limits(*user_id).await {
+                tracing::error!("Failed to recompute AI limits for {}: {:?}", user_id, e);
+            }
+        }
+
+        Ok(user_ids.len())
+    }
+}
+
+// ============================================================================
+// SCHEDULER
+// ============================================================================
+
+/// Hourly monthly-reset job (periods end on each user's own anniversary date)
+pub struct AiQuotaResetScheduler {
+    pool: PgPool,
+}
+
+impl AiQuotaResetScheduler {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn run(&self) {
+        let mut ticker = tokio::time::interval(Duration::from_secs(3600));
+        let service = AiQuotaService::new(self.pool.clone());
+
+        loop {
+            ticker.tick().await;
+            if crate::services::MaintenanceService::pauses("ai_quota_reset")
+                || !crate::services::SchedulerLeader::leads("ai_quota_reset").await
+            {
+                continue;
+            }
+
+            match service.reset_expired_periods().await {
+                Ok(0) => {}
+                Ok(count) => tracing::info!("AI quota period reset for {} users", count),
+                Err(e) => tracing::error!("AI quota reset failed: {:?}", e),
+            }
+        }
+    }
+}
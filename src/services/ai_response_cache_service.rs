@@ -0,0 +1,314 @@
+/// AI response cache - reuses validated Claude responses for identical requests
+///
+/// Requests are keyed by a SHA-256 of the serialized request (model, system prompt,
+/// messages, sampling settings) plus the feature and user, so re-analyzing the same
+/// sync log or re-running the same import mapping costs neither quota nor API spend.
+/// Only responses the caller has parsed and validated are stored.
+///
+/// Configuration:
+/// - AI_CACHE_ENABLED (default true)
+/// - AI_CACHE_TTL_SECONDS overrides every feature's default TTL
+/// - AI_CACHE_DISABLED_FEATURES / AI_CACHE_ENABLED_FEATURES: comma-separated feature
+///   names to opt out of (or into, for features that are uncached by default)
+
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+use crate::middleware::error_handling::Result;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AiFeature {
+    ImportAnalysis,
+    DocumentExtraction,
+    NlQuery,
+    InquiryAssistant,
+    PricingSuggestion,
+    RegulatoryDocument,
+    ErpMappingDiscovery,
+    ErpSyncAnalysis,
+    ErpConflictResolution,
+}
+
+impl AiFeature {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AiFeature::ImportAnalysis => "import_analysis",
+            AiFeature::DocumentExtraction => "document_extraction",
+            AiFeature::NlQuery => "nl_query",
+            AiFeature::InquiryAssistant => "inquiry_assistant",
+            AiFeature::PricingSuggestion => "pricing_suggestion",
+            AiFeature::RegulatoryDocument => "regulatory_document",
+            AiFeature::ErpMappingDiscovery => "erp_mapping_discovery",
+            AiFeature::ErpSyncAnalysis => "erp_sync_analysis",
+            AiFeature::ErpConflictResolution => "erp_conflict_resolution",
+        }
+    }
+
+    /// How long a response stays valid. Prompts embed the data they are about, so changed
+    /// data produces a new key; the TTL bounds how long model output is reused at all.
+    pub fn default_ttl(&self) -> Duration {
+        match self {
+            AiFeature::ImportAnalysis | AiFeature::DocumentExtraction => Duration::days(30),
+            AiFeature::ErpMappingDiscovery | AiFeature::ErpSyncAnalysis => Duration::days(7),
+            AiFeature::NlQuery | AiFeature::ErpConflictResolution => Duration::days(1),
+            AiFeature::PricingSuggestion => Duration::hours(6),
+            AiFeature::InquiryAssistant | AiFeature::RegulatoryDocument => Duration::hours(1),
+        }
+    }
+
+    /// Inquiry replies are expected to vary when regenerated and regulatory documents are
+    /// generated once per signed record, so neither is cached unless opted in
+    fn cached_by_default(&self) -> bool {
+        !matches!(self, AiFeature::InquiryAssistant | AiFeature::RegulatoryDocument)
+    }
+}
+
+/// Cache settings from the environment
+#[derive(Debug, Clone)]
+pub struct AiCacheConfig {
+    pub enabled: bool,
+    pub ttl_override: Option<Duration>,
+    pub disabled_features: Vec<String>,
+    pub enabled_features: Vec<String>,
+}
+
+impl AiCacheConfig {
+    pub fn from_env() -> Self {
+        let list = |name: &str| -> Vec<String> {
+            std::env::var(name)
+                .unwrap_or_default()
+                .split(',')
+                .map(|f| f.trim().to_lowercase())
+                .filter(|f| !f.is_empty())
+                .collect()
+        };
+
+        Self {
+            enabled: std::env::var("AI_CACHE_ENABLED")
+                .map(|v| v != "false" && v != "0")
+                .unwrap_or(true),
+            ttl_override: std::env::var("AI_CACHE_TTL_SECONDS")
+                .ok()
+                .and_then(|s| s.parse::<i64>().ok())
+                .map(Duration::seconds),
+            disabled_features: list("AI_CACHE_DISABLED_FEATURES"),
+            enabled_features: list("AI_CACHE_ENABLED_FEATURES"),
+        }
+    }
+
+    pub fn is_enabled(&self, feature: AiFeature) -> bool {
+        let name = feature.as_str();
+        self.enabled
+            && !self.disabled_features.iter().any(|f| f == name)
+            && (feature.cached_by_default() || self.enabled_features.iter().any(|f| f == name))
+    }
+
+    pub fn ttl(&self, feature: AiFeature) -> Duration {
+        self.ttl_override.unwrap_or_else(|| feature.default_ttl())
+    }
+}
+
+/// Identifies the cache entry a response was (or will be) stored under
+#[derive(Debug, Clone)]
+pub struct AiCacheKey {
+    pub feature: AiFeature,
+    pub user_id: Uuid,
+    pub key: String,
+}
+
+impl AiCacheKey {
+    /// `request` is the exact payload sent to the API
+    pub fn new(feature: AiFeature, user_id: Uuid, request: &[u8]) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(feature.as_str().as_bytes());
+        hasher.update(user_id.as_bytes());
+        hasher.update(request);
+
+        Self {
+            feature,
+            user_id,
+            key: hex::encode(hasher.finalize()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct CachedAiResponse {
+    pub content: String,
+    pub input_tokens: i32,
+    pub output_tokens: i32,
+    pub cost_usd: Decimal,
+}
+
+/// Per-feature cache usage for the admin dashboard
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct AiCacheFeatureStats {
+    pub feature: String,
+    pub live_entries: i64,
+    pub expired_entries: i64,
+    pub total_hits: i64,
+    pub saved_tokens: i64,
+    pub saved_cost_usd: Decimal,
+    pub last_hit_at: Option<DateTime<Utc>>,
+}
+
+pub struct AiResponseCacheService {
+    db_pool: PgPool,
+    config: AiCacheConfig,
+}
+
+impl AiResponseCacheService {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self {
+            db_pool,
+            config: AiCacheConfig::from_env(),
+        }
+    }
+
+    pub fn config(&self) -> &AiCacheConfig {
+        &self.config
+    }
+
+    /// A live cached response for the key, recording the hit
+    pub async fn get(&self, key: &AiCacheKey) -> Result<Option<CachedAiResponse>> {
+        let cached = sqlx::query_as::<_, CachedAiResponse>(
+            r#"
+            UPDATE ai_response_cache
+            SET hit_count = hit_count + 1, last_hit_at = NOW()
+            WHERE cache_key = $1 AND expires_at > NOW()
+            RETURNING content, input_tokens, output_tokens, cost_usd
+            "#
+        )
+        .bind(&key.key)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        crate::middleware::metrics::record_ai_cache_lookup(
+            key.feature.as_str(),
+            cached.as_ref().map(|c| c.cost_usd.to_f64().unwrap_or(0.0)),
+        );
+
+        Ok(cached)
+    }
+
+    /// Store a validated response, replacing an expired entry under the same key
+    pub async fn put(
+        &self,
+        key: &AiCacheKey,
+        content: &str,
+        input_tokens: u32,
+        output_tokens: u32,
+        cost_usd: f64,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO ai_response_cache (
+                cache_key, feature, user_id, content, input_tokens, output_tokens, cost_usd, expires_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (cache_key) DO UPDATE SET
+                content = EXCLUDED.content,
+                input_tokens = EXCLUDED.input_tokens,
+                output_tokens = EXCLUDED.output_tokens,
+                cost_usd = EXCLUDED.cost_usd,
+                hit_count = 0,
+                last_hit_at = NULL,
+                expires_at = EXCLUDED.expires_at,
+                created_at = NOW()
+            "#
+        )
+        .bind(&key.key)
+        .bind(key.feature.as_str())
+        .bind(key.user_id)
+        .bind(content)
+        .bind(input_tokens as i32)
+        .bind(output_tokens as i32)
+        .bind(Decimal::try_from(cost_usd).unwrap_or_default())
+        .bind(Utc::now() + self.config.ttl(key.feature))
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Remove expired entries, or every entry of a feature when one is given
+    pub async fn purge(&self, feature: Option<&str>) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM ai_response_cache
+            WHERE ($1::text IS NULL AND expires_at <= NOW()) OR feature = $1
+            "#
+        )
+        .bind(feature)
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    pub async fn stats(&self) -> Result<Vec<AiCacheFeatureStats>> {
+        let stats = sqlx::query_as::<_, AiCacheFeatureStats>(
+            r#"
+            SELECT
+                feature,
+                COUNT(*) FILTER (WHERE expires_at > NOW()) AS live_entries,
+                COUNT(*) FILTER (WHERE expires_at <= NOW()) AS expired_entries,
+                COALESCE(SUM(hit_count), 0)::BIGINT AS total_hits,
+                COALESCE(SUM(hit_count::BIGINT * (input_tokens + output_tokens)), 0)::BIGINT AS saved_tokens,
+                COALESCE(SUM(hit_count * cost_usd), 0) AS saved_cost_usd,
+                MAX(last_hit_at) AS last_hit_at
+            FROM ai_response_cache
+            GROUP BY feature
+            ORDER BY feature
+            "#
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(disabled: &[&str], enabled: &[&str]) -> AiCacheConfig {
+        AiCacheConfig {
+            enabled: true,
+            ttl_override: None,
+            disabled_features: disabled.iter().map(|s| s.to_string()).collect(),
+            enabled_features: enabled.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_cache_key_scoped_to_feature_and_user() {
+        let user = Uuid::new_v4();
+        let request = br#"{"model":"m","messages":[]}"#;
+
+        let key = AiCacheKey::new(AiFeature::NlQuery, user, request);
+        assert_eq!(key.key.len(), 64);
+        assert_eq!(key.key, AiCacheKey::new(AiFeature::NlQuery, user, request).key);
+        assert_ne!(key.key, AiCacheKey::new(AiFeature::ImportAnalysis, user, request).key);
+        assert_ne!(key.key, AiCacheKey::new(AiFeature::NlQuery, Uuid::new_v4(), request).key);
+    }
+
+    #[test]
+    fn test_feature_opt_out() {
+        let cfg = config(&["nl_query"], &[]);
+        assert!(!cfg.is_enabled(AiFeature::NlQuery));
+        assert!(cfg.is_enabled(AiFeature::ErpSyncAnalysis));
+        assert!(!cfg.is_enabled(AiFeature::InquiryAssistant));
+
+        let cfg = config(&[], &["inquiry_assistant"]);
+        assert!(cfg.is_enabled(AiFeature::InquiryAssistant));
+
+        let cfg = AiCacheConfig { enabled: false, ..config(&[], &[]) };
+        assert!(!cfg.is_enabled(AiFeature::ErpSyncAnalysis));
+    }
+}
@@ -0,0 +1,559 @@
+/// Alert Channel Service
+///
+/// Slack / Microsoft Teams channels alerts are routed to. Channels connect with an
+/// incoming webhook URL, or for Slack through the account's own OAuth app installed with
+/// the `incoming-webhook` scope (the install hands back the webhook URL of the channel the
+/// installer picked). `NotificationService::create_alert` hands every new alert to
+/// `deliver_in_background`, which posts it to the channels the alert type is routed to.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use chrono::{Duration, Utc};
+use rand::RngCore;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::{
+    middleware::error_handling::{AppError, Result},
+    models::{alert_channel::*, alerts::AlertNotification},
+    services::EncryptionService,
+};
+
+/// Channels an account may connect
+const MAX_CHANNELS_PER_USER: i64 = 20;
+/// How long the user has to finish the Slack app install after starting authorization
+const STATE_TTL_MINUTES: i64 = 15;
+const SLACK_AUTHORIZE_URL: &str = "https://slack.com/oauth/v2/authorize";
+const SLACK_TOKEN_URL: &str = "https://slack.com/api/oauth.v2.access";
+
+#[derive(serde::Deserialize)]
+struct SlackOAuthResponse {
+    ok: bool,
+    error: Option<String>,
+    access_token: Option<String>,
+    team: Option<SlackTeam>,
+    incoming_webhook: Option<SlackIncomingWebhook>,
+}
+
+#[derive(serde::Deserialize)]
+struct SlackTeam {
+    name: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct SlackIncomingWebhook {
+    url: String,
+    channel: Option<String>,
+}
+
+pub struct AlertChannelService {
+    db_pool: PgPool,
+    http_client: reqwest::Client,
+}
+
+impl AlertChannelService {
+    pub fn new(db_pool: PgPool) -> Self {
+        let http_client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(10))
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .unwrap_or_default();
+
+        Self { db_pool, http_client }
+    }
+
+    // ========================================================================
+    // CHANNELS
+    // ========================================================================
+
+    /// Connect a channel. Incoming webhook channels are active right away; OAuth channels
+    /// wait for `start_authorization` / `complete_authorization`.
+    pub async fn create_channel(&self, user_id: Uuid, request: CreateAlertChannelRequest) -> Result<AlertChannel> {
+        let encryption = encryption_service()?;
+
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM alert_channels WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_one(&self.db_pool)
+            .await?;
+        if count >= MAX_CHANNELS_PER_USER {
+            return Err(AppError::BadRequest(format!(
+                "At most {} alert channels can be connected",
+                MAX_CHANNELS_PER_USER
+            )));
+        }
+
+        let (webhook_url, client_id, client_secret, status) = match request.connection_type {
+            ChannelConnectionType::IncomingWebhook => {
+                let url = request.webhook_url.as_deref().ok_or_else(|| {
+                    AppError::BadRequest("webhook_url is required for incoming webhook channels".to_string())
+                })?;
+                let url = validate_webhook_url(request.provider, url)?;
+                (Some(encryption.encrypt(&url)?), None, None, "active")
+            }
+            ChannelConnectionType::Oauth => {
+                if request.provider != ChatProvider::Slack {
+                    return Err(AppError::BadRequest(
+                        "Teams channels connect with an incoming webhook (connector or Workflows) URL".to_string(),
+                    ));
+                }
+                let (Some(client_id), Some(client_secret)) = (&request.client_id, &request.client_secret) else {
+                    return Err(AppError::BadRequest(
+                        "client_id and client_secret are required for OAuth channels".to_string(),
+                    ));
+                };
+                (
+                    None,
+                    Some(encryption.encrypt(client_id.trim())?),
+                    Some(encryption.encrypt(client_secret.trim())?),
+                    "pending_authorization",
+                )
+            }
+        };
+
+        let channel = sqlx::query_as::<_, AlertChannel>(
+            r#"
+            INSERT INTO alert_channels (
+                user_id, provider, connection_type, name, status, webhook_url, channel_name, client_id, client_secret
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING *
+            "#
+        )
+        .bind(user_id)
+        .bind(request.provider.as_str())
+        .bind(request.connection_type.as_str())
+        .bind(request.name.trim())
+        .bind(status)
+        .bind(webhook_url)
+        .bind(request.channel_name.as_deref().map(str::trim))
+        .bind(client_id)
+        .bind(client_secret)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        tracing::info!(
+            "Alert channel {} ({} {}) created by user {}",
+            channel.id, channel.provider, channel.connection_type, user_id
+        );
+
+        Ok(channel)
+    }
+
+    pub async fn list_channels(&self, user_id: Uuid) -> Result<Vec<AlertChannel>> {
+        let channels = sqlx::query_as::<_, AlertChannel>(
+            "SELECT * FROM alert_channels WHERE user_id = $1 ORDER BY created_at"
+        )
+        .bind(user_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(channels)
+    }
+
+    pub async fn get_channel(&self, channel_id: Uuid, user_id: Uuid) -> Result<AlertChannel> {
+        sqlx::query_as::<_, AlertChannel>("SELECT * FROM alert_channels WHERE id = $1 AND user_id = $2")
+            .bind(channel_id)
+            .bind(user_id)
+            .fetch_optional(&self.db_pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Alert channel not found".to_string()))
+    }
+
+    pub async fn update_channel(
+        &self,
+        channel_id: Uuid,
+        user_id: Uuid,
+        request: UpdateAlertChannelRequest,
+    ) -> Result<AlertChannel> {
+        let channel = self.get_channel(channel_id, user_id).await?;
+        let provider = ChatProvider::parse(&channel.provider)
+            .ok_or_else(|| AppError::Internal(anyhow::anyhow!("Unknown chat provider {}", channel.provider)))?;
+
+        let webhook_url = match request.webhook_url.as_deref() {
+            Some(_) if channel.connection_type != ChannelConnectionType::IncomingWebhook.as_str() => {
+                return Err(AppError::BadRequest(
+                    "The webhook of an OAuth channel is set by authorizing it again".to_string(),
+                ));
+            }
+            Some(url) => Some(encryption_service()?.encrypt(&validate_webhook_url(provider, url)?)?),
+            None => None,
+        };
+
+        // A new webhook URL clears a delivery error; is_active toggles the channel explicitly
+        let status = match request.is_active {
+            Some(false) => Some("disabled"),
+            Some(true) if channel.webhook_url.is_none() && webhook_url.is_none() => {
+                return Err(AppError::BadRequest("Authorize the channel before activating it".to_string()));
+            }
+            Some(true) => Some("active"),
+            None if webhook_url.is_some() && channel.status == "error" => Some("active"),
+            None => None,
+        };
+
+        let channel = sqlx::query_as::<_, AlertChannel>(
+            r#"
+            UPDATE alert_channels
+            SET name = COALESCE($3, name),
+                channel_name = COALESCE($4, channel_name),
+                webhook_url = COALESCE($5, webhook_url),
+                status = COALESCE($6, status),
+                last_error = CASE WHEN $6 = 'active' THEN NULL ELSE last_error END,
+                updated_at = NOW()
+            WHERE id = $1 AND user_id = $2
+            RETURNING *
+            "#
+        )
+        .bind(channel_id)
+        .bind(user_id)
+        .bind(request.name.as_deref().map(str::trim))
+        .bind(request.channel_name.as_deref().map(str::trim))
+        .bind(webhook_url)
+        .bind(status)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        Ok(channel)
+    }
+
+    /// Delete a channel and drop it from the account's alert routes
+    pub async fn delete_channel(&self, channel_id: Uuid, user_id: Uuid) -> Result<()> {
+        let mut tx = self.db_pool.begin().await?;
+
+        let result = sqlx::query("DELETE FROM alert_channels WHERE id = $1 AND user_id = $2")
+            .bind(channel_id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Alert channel not found".to_string()));
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE user_alert_preferences
+            SET channel_routes = COALESCE(
+                (SELECT jsonb_object_agg(key, value - $2) FROM jsonb_each(channel_routes)),
+                '{}'::jsonb
+            )
+            WHERE user_id = $1
+            "#
+        )
+        .bind(user_id)
+        .bind(channel_id.to_string())
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    // ========================================================================
+    // SLACK OAUTH
+    // ========================================================================
+
+    /// Issue a fresh state and the Slack URL the user installs the app at. Also used to
+    /// point an OAuth channel at a different Slack channel.
+    pub async fn start_authorization(&self, channel: &AlertChannel) -> Result<AlertChannelAuthorizationResponse> {
+        let client_id = match (channel.connection_type.as_str(), &channel.client_id) {
+            ("oauth", Some(client_id)) => encryption_service()?.decrypt(client_id)?,
+            _ => return Err(AppError::BadRequest("Only OAuth channels can be authorized".to_string())),
+        };
+
+        let mut bytes = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        let state = URL_SAFE_NO_PAD.encode(bytes);
+
+        let channel = sqlx::query_as::<_, AlertChannel>(
+            r#"
+            UPDATE alert_channels
+            SET oauth_state_hash = $2, oauth_state_expires_at = $3, updated_at = NOW()
+            WHERE id = $1
+            RETURNING *
+            "#
+        )
+        .bind(channel.id)
+        .bind(EncryptionService::hash_for_lookup(&state))
+        .bind(Utc::now() + Duration::minutes(STATE_TTL_MINUTES))
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        let mut url = url::Url::parse(SLACK_AUTHORIZE_URL)
+            .map_err(|e| AppError::Internal(anyhow::anyhow!("Invalid Slack authorize URL: {}", e)))?;
+        url.query_pairs_mut()
+            .append_pair("client_id", &client_id)
+            .append_pair("scope", "incoming-webhook")
+            .append_pair("state", &state);
+        if let Some(redirect_uri) = redirect_uri() {
+            url.query_pairs_mut().append_pair("redirect_uri", &redirect_uri);
+        }
+
+        Ok(AlertChannelAuthorizationResponse {
+            authorization_url: url.to_string(),
+            channel,
+        })
+    }
+
+    /// Exchange the authorization code; the install returns the channel's webhook URL
+    pub async fn complete_authorization(
+        &self,
+        channel: &AlertChannel,
+        request: &CompleteAlertChannelAuthorizationRequest,
+    ) -> Result<AlertChannel> {
+        // The state is single use
+        let valid: Option<Uuid> = sqlx::query_scalar(
+            r#"
+            UPDATE alert_channels
+            SET oauth_state_hash = NULL, oauth_state_expires_at = NULL
+            WHERE id = $1 AND oauth_state_hash = $2 AND oauth_state_expires_at > NOW()
+            RETURNING id
+            "#
+        )
+        .bind(channel.id)
+        .bind(EncryptionService::hash_for_lookup(&request.state))
+        .fetch_optional(&self.db_pool)
+        .await?;
+        if valid.is_none() {
+            return Err(AppError::BadRequest("Invalid or expired authorization state".to_string()));
+        }
+
+        let encryption = encryption_service()?;
+        let (Some(client_id), Some(client_secret)) = (&channel.client_id, &channel.client_secret) else {
+            return Err(AppError::BadRequest("Only OAuth channels can be authorized".to_string()));
+        };
+        let client_id = encryption.decrypt(client_id)?;
+        let client_secret = encryption.decrypt(client_secret)?;
+
+        let mut form = vec![
+            ("client_id", client_id),
+            ("client_secret", client_secret),
+            ("code", request.code.clone()),
+        ];
+        if let Some(redirect_uri) = redirect_uri() {
+            form.push(("redirect_uri", redirect_uri));
+        }
+
+        let response: SlackOAuthResponse = self
+            .http_client
+            .post(SLACK_TOKEN_URL)
+            .form(&form)
+            .send()
+            .await
+            .map_err(|e| AppError::Internal(anyhow::anyhow!("Slack OAuth request failed: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| AppError::Internal(anyhow::anyhow!("Invalid Slack OAuth response: {}", e)))?;
+
+        if !response.ok {
+            return Err(AppError::BadRequest(format!(
+                "Slack authorization failed: {}",
+                response.error.unwrap_or_else(|| "unknown error".to_string())
+            )));
+        }
+        let webhook = response.incoming_webhook.ok_or_else(|| {
+            AppError::BadRequest("The Slack app must request the incoming-webhook scope".to_string())
+        })?;
+        let webhook_url = validate_webhook_url(ChatProvider::Slack, &webhook.url)?;
+        let access_token = response.access_token.map(|t| encryption.encrypt(&t)).transpose()?;
+
+        let channel = sqlx::query_as::<_, AlertChannel>(
+            r#"
+            UPDATE alert_channels
+            SET webhook_url = $2, channel_name = COALESCE($3, channel_name), workspace_name = $4,
+                access_token = $5, status = 'active', last_error = NULL, updated_at = NOW()
+            WHERE id = $1
+            RETURNING *
+            "#
+        )
+        .bind(channel.id)
+        .bind(encryption.encrypt(&webhook_url)?)
+        .bind(webhook.channel)
+        .bind(response.team.and_then(|t| t.name))
+        .bind(access_token)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        tracing::info!("Alert channel {} authorized with Slack", channel.id);
+
+        Ok(channel)
+    }
+
+    // ========================================================================
+    // DELIVERY
+    // ========================================================================
+
+    /// Post an alert to the active channels its type is routed to. Returns the number of
+    /// channels it was posted to.
+    pub async fn deliver(&self, alert: &AlertNotification) -> Result<usize> {
+        let channels = sqlx::query_as::<_, AlertChannel>(
+            r#"
+            SELECT c.* FROM alert_channels c
+            JOIN user_alert_preferences p ON p.user_id = c.user_id
+            WHERE c.user_id = $1
+              AND c.status = 'active'
+              AND p.channel_routes -> $2 ? c.id::text
+            "#
+        )
+        .bind(alert.user_id)
+        .bind(&alert.alert_type)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        if channels.is_empty() {
+            return Ok(0);
+        }
+
+        let encryption = encryption_service()?;
+        let mut delivered = 0;
+        for channel in &channels {
+            match self.post(channel, alert, &encryption).await {
+                Ok(()) => delivered += 1,
+                Err(e) => tracing::warn!("Alert {} not posted to channel {}: {}", alert.id, channel.id, e),
+            }
+        }
+
+        Ok(delivered)
+    }
+
+    /// Deliver without holding up the caller; failures are only logged
+    pub fn deliver_in_background(db_pool: PgPool, alert: AlertNotification) {
+        tokio::spawn(async move {
+            let service = AlertChannelService::new(db_pool);
+            if let Err(e) = service.deliver(&alert).await {
+                tracing::warn!("Failed to route alert {} to chat channels: {}", alert.id, e);
+            }
+        });
+    }
+
+    /// Post a sample alert so the user can check the channel and the message layout
+    pub async fn send_test(&self, channel_id: Uuid, user_id: Uuid) -> Result<AlertChannel> {
+        let channel = self.get_channel(channel_id, user_id).await?;
+        if channel.webhook_url.is_none() {
+            return Err(AppError::BadRequest("Authorize the channel before testing it".to_string()));
+        }
+
+        let alert = AlertNotification {
+            id: Uuid::new_v4(),
+            user_id,
+            alert_type: "system".to_string(),
+            severity: "info".to_string(),
+            title: "Test alert".to_string(),
+            message: format!("Alerts routed to \"{}\" will be posted here.", channel.name),
+            inventory_id: None,
+            related_user_id: None,
+            metadata: Some(serde_json::json!({ "source": "Atlas PharmaTech" })),
+            action_url: Some("/dashboard/alerts".to_string()),
+            is_read: false,
+            is_dismissed: false,
+            created_at: Utc::now(),
+            read_at: None,
+            dismissed_at: None,
+            held_until: None,
+            digest_id: None,
+            archived_at: None,
+        };
+
+        self.post(&channel, &alert, &encryption_service()?).await?;
+        self.get_channel(channel_id, user_id).await
+    }
+
+    async fn post(&self, channel: &AlertChannel, alert: &AlertNotification, encryption: &EncryptionService) -> Result<()> {
+        let webhook_url = channel
+            .webhook_url
+            .as_deref()
+            .ok_or_else(|| AppError::BadRequest("Channel has no webhook".to_string()))?;
+        let webhook_url = encryption.decrypt(webhook_url)?;
+
+        let link = alert_link(alert, frontend_base_url().as_deref());
+        let message = match ChatProvider::parse(&channel.provider) {
+            Some(ChatProvider::Slack) => slack_message(alert, link.as_deref()),
+            Some(ChatProvider::Teams) => teams_message(alert, link.as_deref()),
+            None => return Err(AppError::Internal(anyhow::anyhow!("Unknown chat provider {}", channel.provider))),
+        };
+
+        let error = match self.http_client.post(&webhook_url).json(&message).send().await {
+            Ok(response) if response.status().is_success() => None,
+            Ok(response) => {
+                let status = response.status();
+                let body: String = response.text().await.unwrap_or_default().chars().take(500).collect();
+                // A removed webhook, archived channel or revoked app will not come back by itself
+                let permanent = status.is_client_error() && status != reqwest::StatusCode::TOO_MANY_REQUESTS;
+                Some((format!("HTTP {}: {}", status.as_u16(), body), permanent))
+            }
+            Err(e) => Some((e.to_string(), false)),
+        };
+
+        match error {
+            None => {
+                sqlx::query(
+                    "UPDATE alert_channels SET last_delivery_at = NOW(), last_error = NULL WHERE id = $1"
+                )
+                .bind(channel.id)
+                .execute(&self.db_pool)
+                .await?;
+                Ok(())
+            }
+            Some((message, permanent)) => {
+                sqlx::query(
+                    r#"
+                    UPDATE alert_channels
+                    SET last_error = $2, status = CASE WHEN $3 THEN 'error' ELSE status END, updated_at = NOW()
+                    WHERE id = $1
+                    "#
+                )
+                .bind(channel.id)
+                .bind(&message)
+                .bind(permanent)
+                .execute(&self.db_pool)
+                .await?;
+                Err(AppError::BadRequest(format!("{} rejected the message: {}", channel.provider, message)))
+            }
+        }
+    }
+}
+
+fn validate_webhook_url(provider: ChatProvider, url: &str) -> Result<String> {
+    let url = url.trim();
+    let parsed = url::Url::parse(url)
+        .map_err(|_| AppError::InvalidInput("Invalid webhook_url".to_string()))?;
+
+    // 🔒 SECURITY: Alerts are only posted to the provider's own HTTPS webhook hosts
+    let host = parsed.host_str().unwrap_or_default();
+    if parsed.scheme() != "https" || !provider.is_webhook_host(host) {
+        return Err(AppError::InvalidInput(format!(
+            "webhook_url must be an https:// {} incoming webhook URL",
+            match provider {
+                ChatProvider::Slack => "Slack",
+                ChatProvider::Teams => "Microsoft Teams",
+            }
+        )));
+    }
+
+    Ok(url.to_string())
+}
+
+fn redirect_uri() -> Option<String> {
+    std::env::var("ALERT_CHANNEL_OAUTH_REDIRECT_URI").ok().filter(|v| !v.is_empty())
+}
+
+fn frontend_base_url() -> Option<String> {
+    std::env::var("FRONTEND_BASE_URL").ok().filter(|v| !v.is_empty())
+}
+
+fn encryption_service() -> Result<EncryptionService> {
+    let key = std::env::var("ENCRYPTION_KEY")
+        .map_err(|_| AppError::Internal(anyhow::anyhow!("ENCRYPTION_KEY not set")))?;
+    Ok(EncryptionService::new(&key)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_webhook_url_validation() {
+        assert!(validate_webhook_url(ChatProvider::Slack, "https://hooks.slack.com/services/T0/B0/x").is_ok());
+        assert!(validate_webhook_url(ChatProvider::Slack, "http://hooks.slack.com/services/T0/B0/x").is_err());
+        assert!(validate_webhook_url(ChatProvider::Slack, "https://example.com/hook").is_err());
+        assert!(validate_webhook_url(ChatProvider::Teams, "https://contoso.webhook.office.com/webhookb2/x").is_ok());
+        assert!(validate_webhook_url(ChatProvider::Teams, "https://hooks.slack.com/services/T0/B0/x").is_err());
+    }
+}
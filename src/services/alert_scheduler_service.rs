@@ -7,21 +7,43 @@
 /// - Expiry alerts (products expiring soon)
 /// - Low stock alerts (inventory below threshold)
 /// - Watchlist matches (new marketplace listings)
+/// - Watchlist triggers (price drops and back in stock on watched NDCs)
+///
+/// It also releases notifications held back for digests or quiet hours once they are due,
+/// publishes scheduled platform announcements, applies the notification retention
+/// policy (archive, then delete), runs the daily data retention purge, the daily
+/// license expiry check (reminders, listing suspension of expired licenses), the
+/// daily sanctions list sync, the hourly audit log chain anchoring and the daily
+/// auto-rejection of stale verification applications.
+///
+/// Every instance runs the scheduler; each check type is a row in alert_scheduler_jobs
+/// with its own interval, and an instance only runs a check after claiming its lease, so
+/// replicas never run the same check twice. Overdue checks run once after downtime.
 
 use crate::{
-    middleware::error_handling::Result,
+    middleware::error_handling::{AppError, Result},
     models::alerts::*,
     models::inventory::SearchInventoryRequest,
-    services::{NotificationService, InventoryService},
+    services::{AnnouncementService, AuditChainService, DataRetentionService, ListingRightsService, NotificationService, InventoryService, RuntimeSettings, SanctionsListService, VerificationService},
 };
-use chrono::Utc;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
 use sqlx::PgPool;
+use std::time::Duration;
 use uuid::Uuid;
 
+/// How often each job loop checks whether its job is due
+const JOB_POLL_SECONDS: u64 = 30;
+
+/// Watchlists evaluated per watchlist check run; the rest stay due for the next run
+const MAX_WATCHLISTS_PER_RUN: i64 = 2000;
+
 pub struct AlertSchedulerService {
     db_pool: PgPool,
     notification_service: NotificationService,
     inventory_service: InventoryService,
+    /// Lease owner id of this instance
+    instance_id: String,
 }
 
 impl AlertSchedulerService {
@@ -31,10 +53,18 @@ impl AlertSchedulerService {
         let pharma_repo = crate::repositories::PharmaceuticalRepository::new(db_pool.clone());
         let inventory_service = InventoryService::new(inventory_repo, pharma_repo);
 
+        let instance_id = format!(
+            "{}-{}-{}",
+            std::env::var("HOSTNAME").unwrap_or_else(|_| "atlas".to_string()),
+            std::process::id(),
+            &Uuid::new_v4().simple().to_string()[..8]
+        );
+
         Self {
             db_pool,
             notification_service,
             inventory_service,
+            instance_id,
         }
     }
 
@@ -42,63 +72,134 @@ impl AlertSchedulerService {
     // MAIN SCHEDULER ENTRY POINT
     // ========================================================================
 
-    /// Run all scheduled alert checks
-    pub async fn run_scheduled_checks(&self) -> Result<ScheduledRunStats> {
-        let run_id = self.start_processing_log("scheduled_run").await?;
-        let mut stats = ScheduledRunStats::default();
+    /// Run the job loops of all check types until the process exits
+    pub async fn run(&self) {
+        tracing::info!("Alert scheduler running as {}", self.instance_id);
+
+        futures::future::join_all(AlertJobType::ALL.iter().map(|job| self.run_job_loop(*job))).await;
+    }
 
-        tracing::info!("Starting scheduled alert checks: run_id={}", run_id);
+    async fn run_job_loop(&self, job: AlertJobType) {
+        let mut ticker = tokio::time::interval(Duration::from_secs(JOB_POLL_SECONDS));
 
-        // Run checks in parallel for efficiency
-        let (expiry_stats, stock_stats, watchlist_stats) = tokio::join!(
-            self.check_expiry_alerts(),
-            self.check_low_stock_alerts(),
-            self.check_watchlist_alerts()
-        );
+        loop {
+            ticker.tick().await;
+            if crate::services::MaintenanceService::pauses("alerts") {
+                continue;
+            }
 
-        // Aggregate statistics
-        if let Ok(expiry) = expiry_stats {
-            stats.expiry_alerts_generated = expiry;
-        } else {
-            stats.errors_encountered += 1;
-            tracing::error!("Expiry alert check failed: {:?}", expiry_stats);
+            match self.claim_job(job).await {
+                Ok(Some(claimed)) => self.run_claimed_job(job, claimed).await,
+                Ok(None) => {}
+                Err(e) => tracing::error!("Failed to claim alert job {}: {}", job.as_str(), e),
+            }
         }
+    }
 
-        if let Ok(stock) = stock_stats {
-            stats.low_stock_alerts_generated = stock;
-        } else {
-            stats.errors_encountered += 1;
-            tracing::error!("Low stock check failed: {:?}", stock_stats);
+    async fn run_claimed_job(&self, job: AlertJobType, claimed: AlertSchedulerJob) {
+        let (_, missed) = next_job_run(claimed.next_run_at, Utc::now(), claimed.interval_seconds);
+        if missed > 0 {
+            tracing::info!(
+                "Alert job {} is overdue ({} runs missed since {}), catching up with one run",
+                job.as_str(),
+                missed,
+                claimed.next_run_at
+            );
         }
 
-        if let Ok(watchlist) = watchlist_stats {
-            stats.watchlist_alerts_generated = watchlist;
-        } else {
-            stats.errors_encountered += 1;
-            tracing::error!("Watchlist check failed: {:?}", watchlist_stats);
+        let result = match job {
+            AlertJobType::ExpiryCheck => self.check_expiry_alerts().await,
+            AlertJobType::LowStockCheck => self.check_low_stock_alerts().await,
+            AlertJobType::WatchlistCheck => self.check_watchlist_alerts().await,
+            AlertJobType::WatchlistTriggerCheck => self.check_watchlist_triggers().await,
+            AlertJobType::DigestDelivery => self.deliver_held_notifications().await,
+            AlertJobType::AnnouncementDelivery => self.publish_scheduled_announcements().await,
+            AlertJobType::NotificationRetention => self.apply_notification_retention().await,
+            AlertJobType::DataPurge => self.purge_expired_data().await,
+            AlertJobType::LicenseExpiryCheck => self.check_license_expiry().await,
+            AlertJobType::SanctionsListSync => self.sync_sanctions_lists().await,
+            AlertJobType::AuditChainAnchor => self.anchor_audit_chain().await,
+            AlertJobType::VerificationAutoReject => self.auto_reject_stale_verifications().await,
+        };
+
+        let error = match &result {
+            Ok(alerts) => {
+                tracing::debug!("Alert job {} completed: {} alerts", job.as_str(), alerts);
+                None
+            }
+            Err(e) => {
+                tracing::error!("Alert job {} failed: {}", job.as_str(), e);
+                Some(e.to_string())
+            }
+        };
+
+        // Schedule from the due time, not the finish time, so the cadence does not drift
+        let (next_run_at, _) = next_job_run(claimed.next_run_at, Utc::now(), claimed.interval_seconds);
+        if let Err(e) = self.release_job(job, next_run_at, error).await {
+            tracing::error!("Failed to release alert job {}: {}", job.as_str(), e);
         }
+    }
 
-        stats.total_alerts_generated = stats.expiry_alerts_generated
-            + stats.low_stock_alerts_generated
-            + stats.watchlist_alerts_generated;
-
-        // Complete the processing log
-        self.complete_processing_log(
-            run_id,
-            if stats.errors_encountered == 0 { "completed" } else { "failed" },
-            stats.total_alerts_generated,
-            stats.errors_encountered,
-            None,
-        ).await?;
-
-        tracing::info!(
-            "Scheduled alert checks completed: run_id={}, alerts={}, errors={}",
-            run_id,
-            stats.total_alerts_generated,
-            stats.errors_encountered
-        );
+    /// Take the lease of a due job. Only one instance can win: the UPDATE re-checks the
+    /// lease under the row lock.
+    async fn claim_job(&self, job: AlertJobType) -> Result<Option<AlertSchedulerJob>> {
+        let claimed = sqlx::query_as::<_, AlertSchedulerJob>(
+            r#"
+            UPDATE alert_scheduler_jobs
+            SET lease_owner = $2,
+                lease_expires_at = NOW() + make_interval(secs => lease_seconds),
+                last_started_at = NOW(),
+                updated_at = NOW()
+            WHERE job_type = $1
+              AND is_enabled = TRUE
+              AND next_run_at <= NOW()
+              AND (lease_expires_at IS NULL OR lease_expires_at < NOW())
+            RETURNING *
+            "#,
+        )
+        .bind(job.as_str())
+        .bind(&self.instance_id)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        Ok(claimed)
+    }
+
+    async fn release_job(
+        &self,
+        job: AlertJobType,
+        next_run_at: DateTime<Utc>,
+        error: Option<String>,
+    ) -> Result<()> {
+        let result = sqlx::query(
+            r#"
+            UPDATE alert_scheduler_jobs
+            SET lease_owner = NULL,
+                lease_expires_at = NULL,
+                next_run_at = $3,
+                last_completed_at = NOW(),
+                last_status = CASE WHEN $4::TEXT IS NULL THEN 'completed' ELSE 'failed' END,
+                last_error = $4,
+                run_count = run_count + 1,
+                updated_at = NOW()
+            WHERE job_type = $1 AND lease_owner = $2
+            "#,
+        )
+        .bind(job.as_str())
+        .bind(&self.instance_id)
+        .bind(next_run_at)
+        .bind(error)
+        .execute(&self.db_pool)
+        .await?;
 
-        Ok(stats)
+        if result.rows_affected() == 0 {
+            tracing::warn!(
+                "Alert job {} outlived its lease and was taken over by another instance",
+                job.as_str()
+            );
+        }
+
+        Ok(())
     }
 
     // ========================================================================
@@ -269,6 +370,17 @@ impl AlertSchedulerService {
                 match self.notification_service.create_alert(payload).await {
                     Ok(_) => {
                         alerts_created += 1;
+                        crate::services::OutboundWebhookService::emit_in_background(
+                            self.db_pool.clone(),
+                            Some(user_id),
+                            crate::models::webhook::WebhookEventType::InventoryLowStock,
+                            serde_json::json!({
+                                "inventory_id": item.id,
+                                "product_name": product_name,
+                                "quantity": item.quantity,
+                                "threshold": threshold,
+                            }),
+                        );
                         tracing::debug!(
                             "Low stock alert created: user={}, product={}, qty={}",
                             user_id,
@@ -294,24 +406,37 @@ impl AlertSchedulerService {
     // WATCHLIST ALERTS
     // ========================================================================
 
-    /// Check marketplace watchlists for new matches
+    /// Check due marketplace watchlists for new matches (each on its own frequency, users
+    /// on paid plan tiers first)
     pub async fn check_watchlist_alerts(&self) -> Result<i32> {
         let run_id = self.start_processing_log("watchlist_check").await?;
         let mut alerts_created = 0;
 
         tracing::info!("Starting watchlist alert check: run_id={}", run_id);
 
-        // Get all active watchlists
+        // Get active watchlists that are due
         let watchlists = sqlx::query_as!(
             MarketplaceWatchlist,
             r#"
             SELECT w.*
             FROM marketplace_watchlist w
             JOIN user_alert_preferences p ON w.user_id = p.user_id
+            LEFT JOIN user_ai_usage_limits l ON l.user_id = w.user_id
             WHERE w.alert_enabled = TRUE
+              AND w.paused_at IS NULL
+              AND w.next_check_at <= NOW()
               AND p.watchlist_alerts_enabled = TRUE
               AND p.in_app_notifications_enabled = TRUE
-            "#
+            ORDER BY
+                CASE COALESCE(l.plan_tier, 'free')
+                    WHEN 'enterprise' THEN 0
+                    WHEN 'pro' THEN 1
+                    ELSE 2
+                END,
+                w.next_check_at
+            LIMIT $1
+            "#,
+            MAX_WATCHLISTS_PER_RUN
         )
         .fetch_all(&self.db_pool)
         .await?;
@@ -327,7 +452,11 @@ impl AlertSchedulerService {
                 SELECT COUNT(*)::INT as "count!"
                 FROM inventory i
                 JOIN pharmaceuticals p ON i.pharmaceutical_id = p.id
+                JOIN users u ON i.user_id = u.id
                 WHERE i.status = 'available'
+                  AND u.listing_suspended_at IS NULL
+                  AND u.suspended_at IS NULL
+                  AND i.taken_down_at IS NULL
                   AND i.user_id != $1
                   AND ($2::TEXT IS NULL OR
                        p.brand_name ILIKE $2 OR
@@ -358,7 +487,11 @@ impl AlertSchedulerService {
                     SELECT i.id
                     FROM inventory i
                     JOIN pharmaceuticals p ON i.pharmaceutical_id = p.id
+                    JOIN users u ON i.user_id = u.id
                     WHERE i.status = 'available'
+                      AND u.listing_suspended_at IS NULL
+                      AND u.suspended_at IS NULL
+                      AND i.taken_down_at IS NULL
                       AND i.user_id != $1
                       AND ($2::TEXT IS NULL OR
                            p.brand_name ILIKE $2 OR
@@ -399,8 +532,15 @@ impl AlertSchedulerService {
                 }
             }
 
-            // Update watchlist statistics
-            if let Err(e) = self.notification_service.update_watchlist_stats(watchlist.id, match_count).await {
+            // Update watchlist statistics and schedule the next check
+            let frequency = WatchlistCheckFrequency::parse(&watchlist.check_frequency)
+                .unwrap_or(WatchlistCheckFrequency::Hourly);
+            let next_check_at = Utc::now() + frequency.interval();
+            if let Err(e) = self
+                .notification_service
+                .update_watchlist_stats(watchlist.id, match_count, next_check_at)
+                .await
+            {
                 tracing::error!("Failed to update watchlist stats: {}", e);
             }
         }
@@ -412,6 +552,316 @@ impl AlertSchedulerService {
         Ok(alerts_created)
     }
 
+    // ========================================================================
+    // WATCHLIST TRIGGERS
+    // ========================================================================
+
+    /// Evaluate price drop / back in stock triggers against the current marketplace
+    pub async fn check_watchlist_triggers(&self) -> Result<i32> {
+        let run_id = self.start_processing_log("watchlist_trigger_check").await?;
+        let mut alerts_created = 0;
+        let mut errors = 0;
+
+        let triggers = sqlx::query_as::<_, ActiveWatchlistTrigger>(
+            r#"
+            SELECT t.*, w.user_id, w.name AS watchlist_name
+            FROM watchlist_triggers t
+            JOIN marketplace_watchlist w ON w.id = t.watchlist_id
+            JOIN user_alert_preferences p ON p.user_id = w.user_id
+            WHERE t.is_active = TRUE
+              AND w.alert_enabled = TRUE
+              AND w.paused_at IS NULL
+              AND p.watchlist_alerts_enabled = TRUE
+              AND p.in_app_notifications_enabled = TRUE
+            "#,
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        for active in triggers {
+            let ActiveWatchlistTrigger { trigger, user_id, watchlist_name } = active;
+
+            // Available listings of the NDC from other sellers; the cheapest one is linked
+            let market = sqlx::query_as::<_, (Option<Decimal>, i64, Option<Uuid>, Option<String>)>(
+                r#"
+                SELECT
+                    MIN(i.unit_price),
+                    COUNT(*),
+                    (ARRAY_AGG(i.id ORDER BY i.unit_price ASC NULLS LAST))[1],
+                    MAX(p.brand_name)
+                FROM inventory i
+                JOIN pharmaceuticals p ON i.pharmaceutical_id = p.id
+                JOIN users u ON i.user_id = u.id
+                WHERE p.ndc_code = $1
+                  AND i.status = 'available'
+                  AND u.listing_suspended_at IS NULL
+                  AND u.suspended_at IS NULL
+                  AND i.taken_down_at IS NULL
+                  AND i.quantity > 0
+                  AND i.expiry_date > CURRENT_DATE
+                  AND i.user_id != $2
+                "#,
+            )
+            .bind(&trigger.ndc_code)
+            .bind(user_id)
+            .fetch_one(&self.db_pool)
+            .await;
+
+            let (best_price, listing_count, best_listing_id, product_name) = match market {
+                Ok(row) => row,
+                Err(e) => {
+                    tracing::error!("Market snapshot failed for trigger {}: {}", trigger.id, e);
+                    errors += 1;
+                    continue;
+                }
+            };
+            let snapshot = MarketSnapshot { best_price, listing_count };
+            let product_name = product_name.unwrap_or_else(|| trigger.ndc_code.clone());
+
+            let now = Utc::now();
+            let triggered_at = match evaluate_watchlist_trigger(&trigger, &snapshot, now) {
+                TriggerEvaluation::CoolingDown => {
+                    tracing::debug!("Watchlist trigger {} cooling down", trigger.id);
+                    continue;
+                }
+                TriggerEvaluation::Idle => None,
+                TriggerEvaluation::Fire => {
+                    let payload = match WatchlistTriggerType::parse(&trigger.trigger_type) {
+                        Some(WatchlistTriggerType::PriceDrop) => AlertPayload::new_price_drop(
+                            user_id,
+                            &watchlist_name,
+                            &trigger.ndc_code,
+                            &product_name,
+                            best_price.unwrap_or_default(),
+                            trigger.price_threshold.unwrap_or_default(),
+                            trigger.last_best_price,
+                            best_listing_id,
+                        ),
+                        _ => AlertPayload::new_back_in_stock(
+                            user_id,
+                            &watchlist_name,
+                            &trigger.ndc_code,
+                            &product_name,
+                            listing_count,
+                            best_price,
+                            best_listing_id,
+                        ),
+                    };
+
+                    match self.notification_service.create_alert(payload).await {
+                        Ok(_) => {
+                            alerts_created += 1;
+                            Some(now)
+                        }
+                        Err(e) => {
+                            // Leave the state untouched so the next run retries
+                            tracing::error!("Failed to create watchlist trigger alert: {}", e);
+                            errors += 1;
+                            continue;
+                        }
+                    }
+                }
+            };
+
+            if let Err(e) = self
+                .notification_service
+                .record_watchlist_trigger_check(trigger.id, best_price, listing_count > 0, triggered_at)
+                .await
+            {
+                tracing::error!("Failed to record watchlist trigger check: {}", e);
+                errors += 1;
+            }
+        }
+
+        self.complete_processing_log(run_id, "completed", alerts_created, errors, None).await?;
+
+        tracing::info!("Watchlist trigger check completed: {} alerts created", alerts_created);
+
+        Ok(alerts_created)
+    }
+
+    // ========================================================================
+    // DIGESTS AND QUIET HOURS
+    // ========================================================================
+
+    /// Deliver held notifications that are due (digest time reached, quiet hours over)
+    pub async fn deliver_held_notifications(&self) -> Result<i32> {
+        let run_id = self.start_processing_log("digest_delivery").await?;
+
+        match self.notification_service.release_held_notifications().await {
+            Ok(delivered) => {
+                self.complete_processing_log(run_id, "completed", delivered, 0, None).await?;
+                if delivered > 0 {
+                    tracing::info!("Held notification delivery completed: {} notifications/digests delivered", delivered);
+                }
+                Ok(delivered)
+            }
+            Err(e) => {
+                self.complete_processing_log(run_id, "failed", 0, 1, Some(e.to_string())).await?;
+                Err(e)
+            }
+        }
+    }
+
+    // ========================================================================
+    // ANNOUNCEMENTS
+    // ========================================================================
+
+    /// Publish announcements scheduled for a time that has passed
+    pub async fn publish_scheduled_announcements(&self) -> Result<i32> {
+        let run_id = self.start_processing_log("announcement_delivery").await?;
+        let service = AnnouncementService::new(self.db_pool.clone());
+
+        match service.publish_due().await {
+            Ok(published) => {
+                self.complete_processing_log(run_id, "completed", published, 0, None).await?;
+                if published > 0 {
+                    tracing::info!("Scheduled announcement delivery completed: {} announcements published", published);
+                }
+                Ok(published)
+            }
+            Err(e) => {
+                self.complete_processing_log(run_id, "failed", 0, 1, Some(e.to_string())).await?;
+                Err(e)
+            }
+        }
+    }
+
+    // ========================================================================
+    // RETENTION
+    // ========================================================================
+
+    /// Archive and delete old notifications per the configured retention policy.
+    /// Returns the number of notifications archived.
+    pub async fn apply_notification_retention(&self) -> Result<i32> {
+        let run_id = self.start_processing_log("notification_retention").await?;
+        let policy = RetentionPolicy::from_env();
+
+        match self.notification_service.apply_retention(policy).await {
+            Ok((archived, deleted)) => {
+                self.complete_processing_log(run_id, "completed", 0, 0, None).await?;
+                tracing::info!(
+                    "Notification retention completed: {} archived (> {} days), {} deleted (> {} days)",
+                    archived,
+                    policy.archive_after_days,
+                    deleted,
+                    policy.delete_after_days
+                );
+                Ok(archived as i32)
+            }
+            Err(e) => {
+                self.complete_processing_log(run_id, "failed", 0, 1, Some(e.to_string())).await?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Purge data past the retention period of each enabled category (see
+    /// DataRetentionService). Returns the number of rows purged.
+    pub async fn purge_expired_data(&self) -> Result<i32> {
+        let run_id = self.start_processing_log("data_purge").await?;
+        let service = DataRetentionService::new(self.db_pool.clone());
+
+        match service.run_purge(None).await {
+            Ok(run) => {
+                self.complete_processing_log(run_id, "completed", 0, 0, None).await?;
+                Ok(run.total_purged.min(i32::MAX as i64) as i32)
+            }
+            Err(AppError::Conflict) => {
+                tracing::info!("Data purge skipped: another purge run is in progress");
+                self.complete_processing_log(run_id, "completed", 0, 0, None).await?;
+                Ok(0)
+            }
+            Err(e) => {
+                self.complete_processing_log(run_id, "failed", 0, 1, Some(e.to_string())).await?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Send license expiry reminders and suspend or restore listing rights (see
+    /// ListingRightsService). Returns the number of notifications sent.
+    pub async fn check_license_expiry(&self) -> Result<i32> {
+        let run_id = self.start_processing_log("license_expiry_check").await?;
+        let service = ListingRightsService::new(self.db_pool.clone());
+
+        match service.run_expiry_check().await {
+            Ok(notifications) => {
+                self.complete_processing_log(run_id, "completed", notifications, 0, None).await?;
+                Ok(notifications)
+            }
+            Err(e) => {
+                self.complete_processing_log(run_id, "failed", 0, 1, Some(e.to_string())).await?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Refresh the OFAC and EU sanctions lists used by sanctions screening. Sends no
+    /// notifications; the stored entry count is logged.
+    pub async fn sync_sanctions_lists(&self) -> Result<i32> {
+        let run_id = self.start_processing_log("sanctions_list_sync").await?;
+        let service = SanctionsListService::new(self.db_pool.clone());
+
+        match service.sync_all(None).await {
+            Ok(entries) => {
+                tracing::info!("Sanctions lists synced: {} entries", entries);
+                self.complete_processing_log(run_id, "completed", 0, 0, None).await?;
+                Ok(0)
+            }
+            Err(e) => {
+                self.complete_processing_log(run_id, "failed", 0, 1, Some(e.to_string())).await?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Verify the audit log chain since the last anchor and anchor its head. Admins are
+    /// notified by the chain service when verification finds issues.
+    pub async fn anchor_audit_chain(&self) -> Result<i32> {
+        let run_id = self.start_processing_log("audit_chain_anchor").await?;
+        let service = AuditChainService::new(self.db_pool.clone());
+
+        match service.anchor(None).await {
+            Ok(anchor) => {
+                let issues = anchor.as_ref().map_or(0, |a| a.issues_found);
+                self.complete_processing_log(run_id, "completed", 0, issues, None).await?;
+                Ok(0)
+            }
+            Err(e) => {
+                self.complete_processing_log(run_id, "failed", 0, 1, Some(e.to_string())).await?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Reject verification applications without activity for
+    /// `verification.auto_reject_days` (0 disables). Applicants are notified by the
+    /// verification service. Returns the number of applications rejected.
+    pub async fn auto_reject_stale_verifications(&self) -> Result<i32> {
+        let Some(days) = RuntimeSettings::verification_auto_reject_days() else {
+            return Ok(0);
+        };
+
+        let run_id = self.start_processing_log("verification_auto_reject").await?;
+        let service = VerificationService::new(self.db_pool.clone());
+
+        match service.auto_reject_stale(days).await {
+            Ok(rejected) => {
+                let rejected = rejected.min(i32::MAX as usize) as i32;
+                if rejected > 0 {
+                    tracing::info!("Auto-rejected {} stale verification applications (> {} days)", rejected, days);
+                }
+                self.complete_processing_log(run_id, "completed", rejected, 0, None).await?;
+                Ok(rejected)
+            }
+            Err(e) => {
+                self.complete_processing_log(run_id, "failed", 0, 1, Some(e.to_string())).await?;
+                Err(e)
+            }
+        }
+    }
+
     // ========================================================================
     // PROCESSING LOG HELPERS
     // ========================================================================
@@ -462,15 +912,10 @@ impl AlertSchedulerService {
     }
 }
 
-// ============================================================================
-// STATISTICS STRUCTURE
-// ============================================================================
-
-#[derive(Debug, Default, Clone, serde::Serialize)]
-pub struct ScheduledRunStats {
-    pub expiry_alerts_generated: i32,
-    pub low_stock_alerts_generated: i32,
-    pub watchlist_alerts_generated: i32,
-    pub total_alerts_generated: i32,
-    pub errors_encountered: i32,
+#[derive(sqlx::FromRow)]
+struct ActiveWatchlistTrigger {
+    #[sqlx(flatten)]
+    trigger: WatchlistTrigger,
+    user_id: Uuid,
+    watchlist_name: String,
 }
@@ -0,0 +1,338 @@
+/// Announcement Service
+///
+/// Admin platform announcements. Publishing creates a notification for every targeted
+/// user through NotificationService (so quiet hours, the real-time stream and chat
+/// routing apply); the per-user feed and read tracking live here. Announcements with a
+/// future publish_at are published by the alert scheduler.
+
+use crate::{
+    middleware::error_handling::{AppError, Result},
+    models::announcement::*,
+    services::NotificationService,
+};
+use chrono::Utc;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Role / country targeting of announcement `a` for user `u`
+const TARGETS_USER: &str = r#"
+    (cardinality(a.target_roles) = 0 OR u.role::TEXT = ANY(a.target_roles))
+    AND (cardinality(a.target_countries) = 0 OR u.country_code = ANY(a.target_countries))
+"#;
+
+/// Announcement `a` is live: published, not withdrawn, not expired
+const IS_LIVE: &str = r#"
+    a.published_at IS NOT NULL
+    AND a.withdrawn_at IS NULL
+    AND (a.expires_at IS NULL OR a.expires_at > NOW())
+"#;
+
+pub struct AnnouncementService {
+    db_pool: PgPool,
+}
+
+impl AnnouncementService {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self { db_pool }
+    }
+
+    // ========================================================================
+    // ADMIN
+    // ========================================================================
+
+    /// Create an announcement and publish it right away unless it is scheduled for later
+    pub async fn create(&self, admin_id: Uuid, request: CreateAnnouncementRequest) -> Result<Announcement> {
+        let target_countries = normalize_country_codes(&request.target_countries).map_err(AppError::BadRequest)?;
+        let mut target_roles: Vec<&str> = request.target_roles.iter().map(|r| r.as_str()).collect();
+        target_roles.sort_unstable();
+        target_roles.dedup();
+
+        let publish_at = request.publish_at.unwrap_or_else(Utc::now);
+        if request.expires_at.map_or(false, |expires| expires <= publish_at) {
+            return Err(AppError::BadRequest("expires_at must be after publish_at".to_string()));
+        }
+
+        let announcement = sqlx::query_as::<_, Announcement>(
+            r#"
+            INSERT INTO announcements (
+                title, message, category, severity, link_url,
+                target_roles, target_countries, publish_at, expires_at, created_by
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            RETURNING *
+            "#,
+        )
+        .bind(&request.title)
+        .bind(&request.message)
+        .bind(request.category.unwrap_or(AnnouncementCategory::General).as_str())
+        .bind(request.severity.as_ref().map_or("info", |s| s.as_str()))
+        .bind(&request.link_url)
+        .bind(&target_roles)
+        .bind(&target_countries)
+        .bind(publish_at)
+        .bind(request.expires_at)
+        .bind(admin_id)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        tracing::info!(
+            "Announcement created: id={}, publish_at={}, roles={:?}, countries={:?}",
+            announcement.id,
+            announcement.publish_at,
+            announcement.target_roles,
+            announcement.target_countries
+        );
+
+        if announcement.publish_at <= Utc::now() {
+            if let Some(published) = self.publish(announcement.id).await? {
+                return Ok(published);
+            }
+        }
+
+        Ok(announcement)
+    }
+
+    /// All announcements, newest first, with read counts
+    pub async fn list(&self, limit: i64, offset: i64) -> Result<Vec<AnnouncementWithStats>> {
+        let announcements = sqlx::query_as::<_, AnnouncementWithStats>(
+            r#"
+            SELECT a.*,
+                   (SELECT COUNT(*) FROM announcement_reads r WHERE r.announcement_id = a.id) AS read_count
+            FROM announcements a
+            ORDER BY a.publish_at DESC
+            LIMIT $1 OFFSET $2
+            "#,
+        )
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(announcements)
+    }
+
+    /// Withdraw an announcement: it leaves the feed, and notifications not read yet are
+    /// dismissed
+    pub async fn withdraw(&self, announcement_id: Uuid) -> Result<Announcement> {
+        let announcement = sqlx::query_as::<_, Announcement>(
+            r#"
+            UPDATE announcements
+            SET withdrawn_at = COALESCE(withdrawn_at, NOW()), updated_at = NOW()
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(announcement_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Announcement not found".to_string()))?;
+
+        let dismissed = sqlx::query(
+            r#"
+            UPDATE alert_notifications
+            SET is_dismissed = TRUE
+            WHERE alert_type = 'announcement'
+              AND (metadata->>'announcement_id')::UUID = $1
+              AND is_read = FALSE
+              AND is_dismissed = FALSE
+            "#,
+        )
+        .bind(announcement_id)
+        .execute(&self.db_pool)
+        .await?;
+
+        tracing::info!(
+            "Announcement withdrawn: id={}, {} unread notifications dismissed",
+            announcement_id,
+            dismissed.rows_affected()
+        );
+
+        Ok(announcement)
+    }
+
+    // ========================================================================
+    // PUBLISHING
+    // ========================================================================
+
+    /// Publish announcements whose publish_at has passed. Returns the number published.
+    pub async fn publish_due(&self) -> Result<i32> {
+        let due: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            SELECT id FROM announcements
+            WHERE published_at IS NULL
+              AND withdrawn_at IS NULL
+              AND publish_at <= NOW()
+              AND (expires_at IS NULL OR expires_at > NOW())
+            ORDER BY publish_at
+            "#,
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let mut published = 0;
+        for announcement_id in due {
+            if self.publish(announcement_id).await?.is_some() {
+                published += 1;
+            }
+        }
+
+        Ok(published)
+    }
+
+    /// Mark the announcement published and notify every targeted user. Returns None when
+    /// it was already published (or withdrawn) by someone else. Users a notification
+    /// could not be created for still see the announcement in their feed.
+    async fn publish(&self, announcement_id: Uuid) -> Result<Option<Announcement>> {
+        let claimed = sqlx::query_as::<_, Announcement>(
+            r#"
+            UPDATE announcements
+            SET published_at = NOW(), updated_at = NOW()
+            WHERE id = $1 AND published_at IS NULL AND withdrawn_at IS NULL
+            RETURNING *
+            "#,
+        )
+        .bind(announcement_id)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        let Some(announcement) = claimed else {
+            return Ok(None);
+        };
+
+        let recipients: Vec<Uuid> = sqlx::query_scalar(&format!(
+            "SELECT u.id FROM users u, announcements a WHERE a.id = $1 AND {}",
+            TARGETS_USER
+        ))
+        .bind(announcement.id)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let notification_service = NotificationService::new(self.db_pool.clone());
+        let mut delivered = 0;
+        for user_id in &recipients {
+            match notification_service.create_alert(announcement.notification_payload(*user_id)).await {
+                Ok(_) => delivered += 1,
+                Err(e) => tracing::error!(
+                    "Failed to notify user {} of announcement {}: {}",
+                    user_id,
+                    announcement.id,
+                    e
+                ),
+            }
+        }
+
+        let announcement = sqlx::query_as::<_, Announcement>(
+            "UPDATE announcements SET recipient_count = $2, updated_at = NOW() WHERE id = $1 RETURNING *",
+        )
+        .bind(announcement.id)
+        .bind(delivered)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        tracing::info!(
+            "Announcement published: id={}, {}/{} recipients notified",
+            announcement.id,
+            delivered,
+            recipients.len()
+        );
+
+        Ok(Some(announcement))
+    }
+
+    // ========================================================================
+    // USER FEED
+    // ========================================================================
+
+    /// Live announcements targeted at the user, newest first
+    pub async fn feed(&self, user_id: Uuid, query: &AnnouncementFeedQuery) -> Result<AnnouncementFeed> {
+        let server_time = Utc::now();
+        let limit = query.limit.unwrap_or(MAX_FEED_ITEMS).clamp(1, MAX_FEED_ITEMS);
+
+        let announcements = sqlx::query_as::<_, AnnouncementFeedItem>(&format!(
+            r#"
+            SELECT a.id, a.title, a.message, a.category, a.severity, a.link_url,
+                   a.published_at, a.expires_at,
+                   (r.read_at IS NOT NULL) AS is_read, r.read_at
+            FROM announcements a
+            JOIN users u ON u.id = $1
+            LEFT JOIN announcement_reads r ON r.announcement_id = a.id AND r.user_id = $1
+            WHERE {} AND {}
+              AND ($2::TIMESTAMPTZ IS NULL OR a.published_at > $2)
+              AND (NOT $3 OR r.read_at IS NULL)
+            ORDER BY a.published_at DESC
+            LIMIT $4
+            "#,
+            IS_LIVE, TARGETS_USER
+        ))
+        .bind(user_id)
+        .bind(query.since)
+        .bind(query.unread_only.unwrap_or(false))
+        .bind(limit)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let unread_count: i64 = sqlx::query_scalar(&format!(
+            r#"
+            SELECT COUNT(*)
+            FROM announcements a
+            JOIN users u ON u.id = $1
+            WHERE {} AND {}
+              AND NOT EXISTS (
+                  SELECT 1 FROM announcement_reads r WHERE r.announcement_id = a.id AND r.user_id = $1
+              )
+            "#,
+            IS_LIVE, TARGETS_USER
+        ))
+        .bind(user_id)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        Ok(AnnouncementFeed { announcements, unread_count, server_time })
+    }
+
+    /// Mark an announcement read (and its notification)
+    pub async fn mark_read(&self, user_id: Uuid, announcement_id: Uuid) -> Result<()> {
+        let visible: bool = sqlx::query_scalar(&format!(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM announcements a JOIN users u ON u.id = $1
+                WHERE a.id = $2 AND {} AND {}
+            )
+            "#,
+            IS_LIVE, TARGETS_USER
+        ))
+        .bind(user_id)
+        .bind(announcement_id)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        if !visible {
+            return Err(AppError::NotFound("Announcement not found".to_string()));
+        }
+
+        sqlx::query(
+            "INSERT INTO announcement_reads (announcement_id, user_id) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+        )
+        .bind(announcement_id)
+        .bind(user_id)
+        .execute(&self.db_pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            UPDATE alert_notifications
+            SET is_read = TRUE
+            WHERE user_id = $1
+              AND alert_type = 'announcement'
+              AND (metadata->>'announcement_id')::UUID = $2
+              AND is_read = FALSE
+            "#,
+        )
+        .bind(user_id)
+        .bind(announcement_id)
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(())
+    }
+}
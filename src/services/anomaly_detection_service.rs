@@ -0,0 +1,555 @@
+/// Anomaly Detection Service
+///
+/// Scheduled statistical checks over recent marketplace activity:
+/// - Quantity jumps: a listing's quantity multiplied in a single change
+/// - Listing and transaction prices far off market history (robust z-score against
+///   completed transactions and other sellers' listings of the same product)
+/// - Duplicate-looking listings: the same product batch listed by several accounts
+///
+/// Findings are stored once per fingerprint in `anomalies`; admins confirm or dismiss
+/// them, and new high/critical findings notify every admin.
+
+use chrono::{DateTime, Duration, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::{
+    middleware::error_handling::{AppError, Result},
+    models::{alerts::AlertPayload, anomaly::*},
+    services::NotificationService,
+};
+
+pub struct AnomalyDetectionService {
+    db_pool: PgPool,
+    thresholds: AnomalyThresholds,
+}
+
+#[derive(sqlx::FromRow)]
+struct QuantityChangeRow {
+    audit_id: Uuid,
+    inventory_id: Uuid,
+    user_id: Uuid,
+    pharmaceutical_id: Uuid,
+    product_name: String,
+    old_quantity: i32,
+    new_quantity: i32,
+    changed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(sqlx::FromRow)]
+struct PricedRecordRow {
+    id: Uuid,
+    user_id: Uuid,
+    pharmaceutical_id: Uuid,
+    product_name: String,
+    unit_price: f64,
+    history: Vec<f64>,
+}
+
+#[derive(sqlx::FromRow)]
+struct DuplicateGroupRow {
+    pharmaceutical_id: Uuid,
+    product_name: String,
+    batch_key: String,
+    expiry_date: chrono::NaiveDate,
+    inventory_ids: Vec<Uuid>,
+    user_ids: Vec<Uuid>,
+    quantities: Vec<i32>,
+    prices: Vec<Option<f64>>,
+}
+
+impl AnomalyDetectionService {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self {
+            db_pool,
+            thresholds: AnomalyThresholds::from_env(),
+        }
+    }
+
+    // ========================================================================
+    // SCHEDULED DETECTION
+    // ========================================================================
+
+    /// Run every detector over the detection window and store new findings
+    pub async fn run_detection(&self) -> Result<AnomalyDetectionStats> {
+        let mut stats = AnomalyDetectionStats::default();
+        let since = Utc::now() - Duration::hours(self.thresholds.detection_window_hours);
+
+        let (quantity, listing_prices, transaction_prices, duplicates) = tokio::join!(
+            self.detect_quantity_jumps(since),
+            self.detect_listing_price_outliers(since),
+            self.detect_transaction_price_outliers(since),
+            self.detect_duplicate_listings(since)
+        );
+
+        let mut findings = Vec::new();
+        for (name, result, counter) in [
+            ("Quantity jump", quantity, &mut stats.quantity_jumps),
+            ("Listing price", listing_prices, &mut stats.listing_price_outliers),
+            ("Transaction price", transaction_prices, &mut stats.transaction_price_outliers),
+            ("Duplicate listing", duplicates, &mut stats.duplicate_listings),
+        ] {
+            match result {
+                Ok(detected) => {
+                    let stored = self.store_findings(&detected).await?;
+                    *counter = stored.len() as i32;
+                    findings.extend(stored);
+                }
+                Err(e) => {
+                    stats.errors_encountered += 1;
+                    tracing::error!("{} anomaly check failed: {:?}", name, e);
+                }
+            }
+        }
+
+        stats.total_new = findings.len() as i32;
+
+        if let Err(e) = self.notify_admins(&findings).await {
+            tracing::error!("Failed to notify admins about new anomalies: {:?}", e);
+        }
+
+        Ok(stats)
+    }
+
+    /// Listings whose quantity grew by a large factor in one change
+    async fn detect_quantity_jumps(&self, since: DateTime<Utc>) -> Result<Vec<DetectedAnomaly>> {
+        let rows = sqlx::query_as::<_, QuantityChangeRow>(
+            r#"
+            SELECT
+                a.id AS audit_id, a.inventory_id, i.user_id, i.pharmaceutical_id,
+                p.brand_name AS product_name, a.old_quantity, a.new_quantity,
+                a.timestamp AS changed_at
+            FROM inventory_audit a
+            JOIN inventory i ON i.id = a.inventory_id
+            JOIN pharmaceuticals p ON p.id = i.pharmaceutical_id
+            WHERE a.action = 'quantity_changed'
+              AND a.timestamp >= $1
+              AND a.old_quantity > 0
+              AND a.new_quantity > a.old_quantity
+            "#
+        )
+        .bind(since)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| {
+                let (factor, severity) = quantity_jump(
+                    row.old_quantity as i64,
+                    row.new_quantity as i64,
+                    &self.thresholds,
+                )?;
+
+                Some(DetectedAnomaly {
+                    anomaly_type: "quantity_jump",
+                    severity,
+                    entity_type: "inventory",
+                    entity_id: row.inventory_id,
+                    user_id: Some(row.user_id),
+                    pharmaceutical_id: Some(row.pharmaceutical_id),
+                    score: factor,
+                    description: format!(
+                        "{} quantity jumped from {} to {} ({:.0}x) in one change",
+                        row.product_name, row.old_quantity, row.new_quantity, factor
+                    ),
+                    details: serde_json::json!({
+                        "old_quantity": row.old_quantity,
+                        "new_quantity": row.new_quantity,
+                        "factor": factor,
+                        "changed_at": row.changed_at,
+                    }),
+                    fingerprint: anomaly_fingerprint("quantity_jump", &[row.audit_id.to_string()]),
+                })
+            })
+            .collect())
+    }
+
+    /// Listings created or changed recently whose price is far off the product's market
+    async fn detect_listing_price_outliers(&self, since: DateTime<Utc>) -> Result<Vec<DetectedAnomaly>> {
+        let rows = sqlx::query_as::<_, PricedRecordRow>(
+            r#"
+            SELECT
+                i.id, i.user_id, i.pharmaceutical_id, p.brand_name AS product_name,
+                i.unit_price::float8 AS unit_price,
+                ARRAY(
+                    SELECT t.unit_price::float8
+                    FROM transactions t
+                    JOIN inquiries q ON q.id = t.inquiry_id
+                    JOIN inventory ti ON ti.id = q.inventory_id
+                    WHERE ti.pharmaceutical_id = i.pharmaceutical_id
+                      AND t.status = 'completed'
+                      AND t.transaction_date >= NOW() - make_interval(days => $2)
+                      AND t.seller_id != i.user_id
+                ) || ARRAY(
+                    SELECT o.unit_price::float8
+                    FROM inventory o
+                    WHERE o.pharmaceutical_id = i.pharmaceutical_id
+                      AND o.user_id != i.user_id
+                      AND o.status = 'available'
+                      AND o.unit_price IS NOT NULL
+                ) AS history
+            FROM inventory i
+            JOIN pharmaceuticals p ON p.id = i.pharmaceutical_id
+            WHERE i.status = 'available'
+              AND i.unit_price IS NOT NULL
+              AND i.updated_at >= $1
+            "#
+        )
+        .bind(since)
+        .bind(self.thresholds.history_days as i32)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| {
+                self.price_finding(row, "listing_price_outlier", "inventory", "Listing")
+            })
+            .collect())
+    }
+
+    /// Recent transactions settled far off the product's market price
+    async fn detect_transaction_price_outliers(&self, since: DateTime<Utc>) -> Result<Vec<DetectedAnomaly>> {
+        let rows = sqlx::query_as::<_, PricedRecordRow>(
+            r#"
+            SELECT
+                t.id, t.seller_id AS user_id, i.pharmaceutical_id, p.brand_name AS product_name,
+                t.unit_price::float8 AS unit_price,
+                ARRAY(
+                    SELECT h.unit_price::float8
+                    FROM transactions h
+                    JOIN inquiries hq ON hq.id = h.inquiry_id
+                    JOIN inventory hi ON hi.id = hq.inventory_id
+                    WHERE hi.pharmaceutical_id = i.pharmaceutical_id
+                      AND h.status = 'completed'
+                      AND h.transaction_date >= NOW() - make_interval(days => $2)
+                      AND h.id != t.id
+                ) AS history
+            FROM transactions t
+            JOIN inquiries q ON q.id = t.inquiry_id
+            JOIN inventory i ON i.id = q.inventory_id
+            JOIN pharmaceuticals p ON p.id = i.pharmaceutical_id
+            WHERE t.status != 'cancelled'
+              AND t.transaction_date >= $1
+            "#
+        )
+        .bind(since)
+        .bind(self.thresholds.history_days as i32)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| {
+                self.price_finding(row, "transaction_price_outlier", "transaction", "Transaction")
+            })
+            .collect())
+    }
+
+    fn price_finding(
+        &self,
+        row: PricedRecordRow,
+        anomaly_type: &'static str,
+        entity_type: &'static str,
+        label: &str,
+    ) -> Option<DetectedAnomaly> {
+        let outlier = price_outlier(row.unit_price, &row.history, &self.thresholds)?;
+
+        Some(DetectedAnomaly {
+            anomaly_type,
+            severity: outlier.severity,
+            entity_type,
+            entity_id: row.id,
+            user_id: Some(row.user_id),
+            pharmaceutical_id: Some(row.pharmaceutical_id),
+            score: outlier.ratio,
+            description: format!(
+                "{} price {:.2} for {} is {:.1}x {} the market median of {:.2}",
+                label,
+                row.unit_price,
+                row.product_name,
+                outlier.ratio,
+                if outlier.above_market { "above" } else { "below" },
+                outlier.market_median
+            ),
+            details: serde_json::json!({
+                "unit_price": row.unit_price,
+                "market_median": outlier.market_median,
+                "ratio": outlier.ratio,
+                "z_score": outlier.z_score.is_finite().then_some(outlier.z_score),
+                "above_market": outlier.above_market,
+                "market_prices": row.history.len(),
+            }),
+            // A changed price is a new finding
+            fingerprint: anomaly_fingerprint(
+                anomaly_type,
+                &[row.id.to_string(), format!("{:.2}", row.unit_price)],
+            ),
+        })
+    }
+
+    /// The same product batch (same normalized batch number and expiry) listed by several
+    /// accounts, where at least one listing is recent
+    async fn detect_duplicate_listings(&self, since: DateTime<Utc>) -> Result<Vec<DetectedAnomaly>> {
+        let groups = sqlx::query_as::<_, DuplicateGroupRow>(
+            r#"
+            SELECT
+                i.pharmaceutical_id,
+                MIN(p.brand_name) AS product_name,
+                UPPER(regexp_replace(i.batch_number, '[^[:alnum:]]', '', 'g')) AS batch_key,
+                i.expiry_date,
+                array_agg(i.id ORDER BY i.created_at) AS inventory_ids,
+                array_agg(i.user_id ORDER BY i.created_at) AS user_ids,
+                array_agg(i.quantity ORDER BY i.created_at) AS quantities,
+                array_agg(i.unit_price::float8 ORDER BY i.created_at) AS prices
+            FROM inventory i
+            JOIN pharmaceuticals p ON p.id = i.pharmaceutical_id
+            WHERE i.status IN ('available', 'reserved')
+            GROUP BY i.pharmaceutical_id, batch_key, i.expiry_date
+            HAVING COUNT(DISTINCT i.user_id) > 1
+               AND MAX(i.updated_at) >= $1
+            "#
+        )
+        .bind(since)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(groups
+            .into_iter()
+            .filter(|group| !group.batch_key.is_empty())
+            .map(|group| {
+                let mut accounts = group.user_ids.clone();
+                accounts.sort();
+                accounts.dedup();
+
+                // Copied listings often carry the exact same quantity and price too
+                let identical_terms = group.quantities.windows(2).all(|w| w[0] == w[1])
+                    && group.prices.windows(2).all(|w| w[0] == w[1]);
+                let severity = if identical_terms { "critical" } else { "high" };
+
+                // Flag the most recent listing; the earliest is most likely the original
+                let last = group.inventory_ids.len() - 1;
+
+                DetectedAnomaly {
+                    anomaly_type: "duplicate_listing",
+                    severity,
+                    entity_type: "inventory",
+                    entity_id: group.inventory_ids[last],
+                    user_id: Some(group.user_ids[last]),
+                    pharmaceutical_id: Some(group.pharmaceutical_id),
+                    score: accounts.len() as f64,
+                    description: format!(
+                        "{} batch {} (expiry {}) is listed by {} accounts",
+                        group.product_name, group.batch_key, group.expiry_date, accounts.len()
+                    ),
+                    details: serde_json::json!({
+                        "batch_key": group.batch_key,
+                        "expiry_date": group.expiry_date,
+                        "inventory_ids": group.inventory_ids,
+                        "user_ids": group.user_ids,
+                        "quantities": group.quantities,
+                        "prices": group.prices,
+                        "identical_terms": identical_terms,
+                    }),
+                    fingerprint: anomaly_fingerprint(
+                        "duplicate_listing",
+                        &group.inventory_ids.iter().map(Uuid::to_string).collect::<Vec<_>>(),
+                    ),
+                }
+            })
+            .collect())
+    }
+
+    /// Insert findings not seen before; returns the ones that were new
+    async fn store_findings(&self, findings: &[DetectedAnomaly]) -> Result<Vec<Anomaly>> {
+        let mut stored = Vec::new();
+
+        for finding in findings {
+            let anomaly = sqlx::query_as::<_, Anomaly>(
+                r#"
+                INSERT INTO anomalies (
+                    anomaly_type, severity, entity_type, entity_id, user_id,
+                    pharmaceutical_id, score, description, details, fingerprint
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                ON CONFLICT (fingerprint) DO NOTHING
+                RETURNING *
+                "#
+            )
+            .bind(finding.anomaly_type)
+            .bind(finding.severity)
+            .bind(finding.entity_type)
+            .bind(finding.entity_id)
+            .bind(finding.user_id)
+            .bind(finding.pharmaceutical_id)
+            .bind(finding.score)
+            .bind(&finding.description)
+            .bind(&finding.details)
+            .bind(&finding.fingerprint)
+            .fetch_optional(&self.db_pool)
+            .await?;
+
+            if let Some(anomaly) = anomaly {
+                tracing::warn!(
+                    "Anomaly {} ({}, {}): {}",
+                    anomaly.id, anomaly.anomaly_type, anomaly.severity, anomaly.description
+                );
+                stored.push(anomaly);
+            }
+        }
+
+        Ok(stored)
+    }
+
+    async fn notify_admins(&self, findings: &[Anomaly]) -> Result<()> {
+        let urgent: Vec<&Anomaly> = findings
+            .iter()
+            .filter(|a| a.severity == "high" || a.severity == "critical")
+            .collect();
+        if urgent.is_empty() {
+            return Ok(());
+        }
+
+        let descriptions: Vec<String> = urgent.iter().map(|a| a.description.clone()).collect();
+        let anomaly_ids: Vec<Uuid> = urgent.iter().map(|a| a.id).collect();
+
+        let admin_ids: Vec<Uuid> = sqlx::query_scalar(
+            "SELECT id FROM users WHERE role IN ('admin', 'superadmin')"
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let notification_service = NotificationService::new(self.db_pool.clone());
+        for admin_id in admin_ids {
+            notification_service
+                .create_alert(AlertPayload::new_anomalies_detected(admin_id, &descriptions, &anomaly_ids))
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    // ========================================================================
+    // ADMIN REVIEW
+    // ========================================================================
+
+    pub async fn list(&self, query: &ListAnomaliesQuery) -> Result<AnomalyListResponse> {
+        let status = query.status.as_deref().unwrap_or("open");
+        if status != "all" && !ANOMALY_STATUSES.contains(&status) {
+            return Err(AppError::InvalidInput(format!("Unknown status '{}'", status)));
+        }
+        if let Some(severity) = query.severity.as_deref() {
+            if !ANOMALY_SEVERITIES.contains(&severity) {
+                return Err(AppError::InvalidInput(format!("Unknown severity '{}'", severity)));
+            }
+        }
+        if let Some(anomaly_type) = query.anomaly_type.as_deref() {
+            if !ANOMALY_TYPES.contains(&anomaly_type) {
+                return Err(AppError::InvalidInput(format!("Unknown anomaly type '{}'", anomaly_type)));
+            }
+        }
+
+        let page = query.page.unwrap_or(1).max(1);
+        let page_size = query.page_size.unwrap_or(50).clamp(1, 200);
+        let status = (status != "all").then_some(status);
+
+        let filter = r#"
+            WHERE ($1::text IS NULL OR status = $1)
+              AND ($2::text IS NULL OR severity = $2)
+              AND ($3::text IS NULL OR anomaly_type = $3)
+              AND ($4::uuid IS NULL OR user_id = $4)
+        "#;
+
+        let anomalies = sqlx::query_as::<_, Anomaly>(&format!(
+            r#"
+            SELECT * FROM anomalies
+            {}
+            ORDER BY
+                CASE severity WHEN 'critical' THEN 0 WHEN 'high' THEN 1 WHEN 'medium' THEN 2 ELSE 3 END,
+                detected_at DESC
+            LIMIT $5 OFFSET $6
+            "#,
+            filter
+        ))
+        .bind(status)
+        .bind(&query.severity)
+        .bind(&query.anomaly_type)
+        .bind(query.user_id)
+        .bind(page_size)
+        .bind((page - 1) * page_size)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let total: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM anomalies {}", filter))
+            .bind(status)
+            .bind(&query.severity)
+            .bind(&query.anomaly_type)
+            .bind(query.user_id)
+            .fetch_one(&self.db_pool)
+            .await?;
+
+        let open_counts = sqlx::query_as::<_, (String, i64)>(
+            "SELECT severity, COUNT(*) FROM anomalies WHERE status = 'open' GROUP BY severity"
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let mut open_by_severity = serde_json::Map::new();
+        for severity in ANOMALY_SEVERITIES {
+            let count = open_counts.iter().find(|(s, _)| s == severity).map_or(0, |(_, c)| *c);
+            open_by_severity.insert(severity.to_string(), count.into());
+        }
+
+        Ok(AnomalyListResponse {
+            anomalies,
+            total,
+            page,
+            page_size,
+            open_by_severity: serde_json::Value::Object(open_by_severity),
+        })
+    }
+
+    pub async fn get(&self, anomaly_id: Uuid) -> Result<Anomaly> {
+        sqlx::query_as::<_, Anomaly>("SELECT * FROM anomalies WHERE id = $1")
+            .bind(anomaly_id)
+            .fetch_optional(&self.db_pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Anomaly not found".to_string()))
+    }
+
+    /// Confirm or dismiss an open finding
+    pub async fn review(
+        &self,
+        anomaly_id: Uuid,
+        status: &str,
+        notes: Option<&str>,
+        admin_id: Uuid,
+    ) -> Result<Anomaly> {
+        if status != "confirmed" && status != "dismissed" {
+            return Err(AppError::InvalidInput(format!("Cannot set anomaly status to '{}'", status)));
+        }
+
+        let reviewed = sqlx::query_as::<_, Anomaly>(
+            r#"
+            UPDATE anomalies
+            SET status = $2, review_notes = $3, reviewed_by = $4, reviewed_at = NOW()
+            WHERE id = $1 AND status = 'open'
+            RETURNING *
+            "#
+        )
+        .bind(anomaly_id)
+        .bind(status)
+        .bind(notes)
+        .bind(admin_id)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        match reviewed {
+            Some(anomaly) => Ok(anomaly),
+            None => {
+                let existing = self.get(anomaly_id).await?;
+                Err(AppError::BadRequest(format!("Anomaly was already {}", existing.status)))
+            }
+        }
+    }
+}
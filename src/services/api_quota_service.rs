@@ -41,10 +41,11 @@ use sqlx::PgPool;
 use uuid::Uuid;
 use chrono::{DateTime, Utc, Datelike};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use crate::middleware::error_handling::{Result, AppError};
 
 /// User quota tier - matches PostgreSQL enum quota_tier exactly
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, ToSchema)]
 #[sqlx(type_name = "quota_tier")]
 pub enum QuotaTier {
     Free,       // 100 requests/month
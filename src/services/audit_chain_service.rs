@@ -0,0 +1,310 @@
+/// Audit Chain Service
+///
+/// Tamper evidence for the audit log written by ComprehensiveAuditService. Every entry
+/// is linked into a hash chain on insert (see migration 079); this service verifies
+/// the chain and anchors its head.
+///
+/// - Verification walks entries and retention tombstones in chain order, recomputing
+///   each entry's hash, and reports gaps, broken links, modified entries, a truncated
+///   tail and mismatches with earlier anchors
+/// - Anchoring (hourly audit_chain_anchor job, or on demand) verifies the segment since
+///   the previous anchor and records the head. With `AUDIT_ANCHOR_URL` set the anchor
+///   is also POSTed there (signed with `AUDIT_ANCHOR_SECRET` when set), so a rewritten
+///   chain can't be re-anchored unnoticed.
+
+use chrono::Utc;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use std::time::Duration;
+use uuid::Uuid;
+use crate::{
+    middleware::error_handling::{AppError, Result},
+    models::{alerts::AlertPayload, audit_chain::*},
+    services::{catalog_subscription_service::sign_payload, NotificationService},
+};
+
+/// Chain links fetched per verification query
+const VERIFY_BATCH_SIZE: i64 = 5000;
+
+pub struct AuditChainService {
+    db_pool: PgPool,
+}
+
+impl AuditChainService {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self { db_pool }
+    }
+
+    pub async fn head(&self) -> Result<Option<AuditChainHead>> {
+        let head = sqlx::query_as::<_, AuditChainHead>(
+            "SELECT chain_seq, entry_hash, updated_at FROM audit_chain_head WHERE id"
+        )
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        Ok(head)
+    }
+
+    pub async fn list_anchors(&self, limit: i64) -> Result<Vec<AuditChainAnchor>> {
+        let anchors = sqlx::query_as::<_, AuditChainAnchor>(
+            "SELECT * FROM audit_chain_anchors ORDER BY chain_seq DESC, anchored_at DESC LIMIT $1"
+        )
+        .bind(limit.clamp(1, 500))
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(anchors)
+    }
+
+    async fn get_anchor(&self, anchor_id: Uuid) -> Result<AuditChainAnchor> {
+        sqlx::query_as::<_, AuditChainAnchor>("SELECT * FROM audit_chain_anchors WHERE id = $1")
+            .bind(anchor_id)
+            .fetch_optional(&self.db_pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Anchor not found".to_string()))
+    }
+
+    async fn latest_anchor(&self) -> Result<Option<AuditChainAnchor>> {
+        let anchor = sqlx::query_as::<_, AuditChainAnchor>(
+            "SELECT * FROM audit_chain_anchors ORDER BY chain_seq DESC, anchored_at DESC LIMIT 1"
+        )
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        Ok(anchor)
+    }
+
+    // ========================================================================
+    // VERIFICATION
+    // ========================================================================
+
+    /// Verify the whole chain, or the part after an anchor
+    pub async fn verify(&self, since_anchor: Option<Uuid>) -> Result<ChainVerificationReport> {
+        let start = match since_anchor {
+            Some(anchor_id) => Some(self.get_anchor(anchor_id).await?),
+            None => None,
+        };
+        self.verify_after(start.as_ref()).await
+    }
+
+    async fn verify_after(&self, start: Option<&AuditChainAnchor>) -> Result<ChainVerificationReport> {
+        let head = self.head().await?;
+        let head_seq = head.as_ref().map_or(0, |h| h.chain_seq);
+        let (after_seq, after_hash) = match start {
+            Some(anchor) => (anchor.chain_seq, anchor.entry_hash.as_str()),
+            None => (0, GENESIS_HASH),
+        };
+
+        let anchors: HashMap<i64, String> = sqlx::query_as::<_, (i64, String)>(
+            "SELECT chain_seq, entry_hash FROM audit_chain_anchors WHERE chain_seq > $1"
+        )
+        .bind(after_seq)
+        .fetch_all(&self.db_pool)
+        .await?
+        .into_iter()
+        .collect();
+
+        let mut verifier = ChainVerifier::new(after_seq, after_hash, anchors);
+        let mut cursor = after_seq;
+
+        loop {
+            let links = sqlx::query_as::<_, ChainLink>(
+                r#"
+                SELECT chain_seq, prev_hash, entry_hash, recomputed_hash, is_tombstone
+                FROM (
+                    SELECT l.chain_seq, l.prev_hash, l.entry_hash,
+                           audit_chain_link(l.prev_hash, audit_log_content_hash(l)) AS recomputed_hash,
+                           FALSE AS is_tombstone
+                    FROM audit_logs l
+                    WHERE l.chain_seq > $1 AND l.chain_seq <= $2
+                    UNION ALL
+                    SELECT t.chain_seq, t.prev_hash, t.entry_hash, NULL, TRUE
+                    FROM audit_chain_tombstones t
+                    WHERE t.chain_seq > $1 AND t.chain_seq <= $2
+                ) links
+                ORDER BY chain_seq, is_tombstone
+                LIMIT $3
+                "#
+            )
+            .bind(cursor)
+            .bind(head_seq)
+            .bind(VERIFY_BATCH_SIZE)
+            .fetch_all(&self.db_pool)
+            .await?;
+
+            let Some(last) = links.last() else { break };
+            cursor = last.chain_seq;
+            for link in &links {
+                verifier.push(link);
+            }
+            if (links.len() as i64) < VERIFY_BATCH_SIZE {
+                break;
+            }
+        }
+
+        let unchained: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM audit_logs WHERE chain_seq IS NULL")
+            .fetch_one(&self.db_pool)
+            .await?;
+
+        Ok(verifier.finish(head.as_ref(), unchained))
+    }
+
+    // ========================================================================
+    // ANCHORING
+    // ========================================================================
+
+    /// Verify the chain since the previous anchor and anchor the current head.
+    /// Returns None when the head hasn't moved since the previous anchor.
+    pub async fn anchor(&self, anchored_by: Option<Uuid>) -> Result<Option<AuditChainAnchor>> {
+        let previous = self.latest_anchor().await?;
+        let report = self.verify_after(previous.as_ref()).await?;
+
+        let head = self
+            .head()
+            .await?
+            .ok_or_else(|| AppError::Internal(anyhow::anyhow!("Audit chain head is missing")))?;
+        if previous.as_ref().map_or(false, |p| p.chain_seq == head.chain_seq && p.entry_hash == head.entry_hash)
+            && report.valid
+        {
+            return Ok(None);
+        }
+
+        // The head may have moved during verification; anchor the verified position
+        let (chain_seq, entry_hash) = if report.head_seq == head.chain_seq {
+            (head.chain_seq, head.entry_hash.clone())
+        } else {
+            let hash: Option<String> = sqlx::query_scalar(
+                r#"
+                SELECT entry_hash FROM audit_logs WHERE chain_seq = $1
+                UNION ALL
+                SELECT entry_hash FROM audit_chain_tombstones WHERE chain_seq = $1
+                LIMIT 1
+                "#
+            )
+            .bind(report.head_seq)
+            .fetch_optional(&self.db_pool)
+            .await?;
+            match hash {
+                Some(hash) => (report.head_seq, hash),
+                None => (head.chain_seq, head.entry_hash.clone()),
+            }
+        };
+
+        let anchored_at = Utc::now();
+        let issues_found = report.issue_count.min(i32::MAX as i64) as i32;
+        let (published_at, publish_error) = match self.publish(chain_seq, &entry_hash, issues_found, anchored_at).await {
+            None => (None, None),
+            Some(Ok(())) => (Some(Utc::now()), None),
+            Some(Err(e)) => {
+                tracing::warn!("Failed to publish audit chain anchor at {}: {}", chain_seq, e);
+                (None, Some(e))
+            }
+        };
+
+        let anchor = sqlx::query_as::<_, AuditChainAnchor>(
+            r#"
+            INSERT INTO audit_chain_anchors (
+                chain_seq, entry_hash, verified_from_seq, issues_found,
+                published_at, publish_error, anchored_by, anchored_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING *
+            "#
+        )
+        .bind(chain_seq)
+        .bind(&entry_hash)
+        .bind(report.from_seq)
+        .bind(issues_found)
+        .bind(published_at)
+        .bind(publish_error)
+        .bind(anchored_by)
+        .bind(anchored_at)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        tracing::info!("Audit chain anchored at {}: {}", anchor.chain_seq, anchor.entry_hash);
+
+        if !report.valid {
+            tracing::error!(
+                "Audit chain verification found {} issue(s) between {} and {}",
+                report.issue_count,
+                report.from_seq,
+                chain_seq
+            );
+            self.notify_admins(&report).await;
+        }
+
+        Ok(Some(anchor))
+    }
+
+    /// POST the anchor to AUDIT_ANCHOR_URL; None when not configured
+    async fn publish(
+        &self,
+        chain_seq: i64,
+        entry_hash: &str,
+        issues_found: i32,
+        anchored_at: chrono::DateTime<Utc>,
+    ) -> Option<std::result::Result<(), String>> {
+        let url = std::env::var("AUDIT_ANCHOR_URL").ok().filter(|u| !u.trim().is_empty())?;
+
+        let body = serde_json::json!({
+            "chain_seq": chain_seq,
+            "entry_hash": entry_hash,
+            "issues_found": issues_found,
+            "anchored_at": anchored_at.to_rfc3339(),
+        })
+        .to_string();
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .unwrap_or_default();
+        let mut request = client
+            .post(url.trim())
+            .header(reqwest::header::CONTENT_TYPE, "application/json");
+
+        if let Ok(secret) = std::env::var("AUDIT_ANCHOR_SECRET") {
+            match sign_payload(&secret, body.as_bytes()) {
+                Ok(signature) => request = request.header("X-Webhook-Signature", signature),
+                Err(e) => return Some(Err(e.to_string())),
+            }
+        }
+
+        let result = match request.body(body).send().await {
+            Ok(response) if response.status().is_success() => Ok(()),
+            Ok(response) => Err(format!("HTTP {}", response.status())),
+            Err(e) => Err(e.to_string()),
+        };
+        Some(result)
+    }
+
+    /// Notifications are best effort; the anchor records the issue count
+    async fn notify_admins(&self, report: &ChainVerificationReport) {
+        let admin_ids: Vec<Uuid> = match sqlx::query_scalar(
+            "SELECT id FROM users WHERE role IN ('admin', 'superadmin')"
+        )
+        .fetch_all(&self.db_pool)
+        .await
+        {
+            Ok(ids) => ids,
+            Err(e) => {
+                tracing::warn!("Could not load admins for audit chain alert: {}", e);
+                return;
+            }
+        };
+
+        let notification_service = NotificationService::new(self.db_pool.clone());
+        for admin_id in admin_ids {
+            let payload = AlertPayload::new_audit_chain_issues(
+                admin_id,
+                report.issue_count,
+                report.from_seq,
+                report.head_seq,
+            );
+            if let Err(e) = notification_service.create_alert(payload).await {
+                tracing::warn!("Could not notify admin {} about audit chain issues: {:?}", admin_id, e);
+            }
+        }
+    }
+}
@@ -210,6 +210,50 @@ impl AuditService {
         ).await
     }
 
+    /// Log row accept/reject decisions made during mapping review
+    pub async fn log_rows_reviewed(
+        &self,
+        session_id: Uuid,
+        user_id: Uuid,
+        rows_accepted: usize,
+        rows_rejected: usize,
+    ) -> Result<()> {
+        self.log_event(
+            session_id,
+            user_id,
+            "rows_reviewed",
+            serde_json::json!({
+                "rows_accepted": rows_accepted,
+                "rows_rejected": rows_rejected,
+                "action": "User reviewed rows before import"
+            }),
+            None,
+        ).await
+    }
+
+    /// Log rollback of a completed import
+    pub async fn log_import_rolled_back(
+        &self,
+        session_id: Uuid,
+        user_id: Uuid,
+        rows_rolled_back: u32,
+        quantity_reversed: i64,
+        rows_skipped: usize,
+    ) -> Result<()> {
+        self.log_event(
+            session_id,
+            user_id,
+            "import_rolled_back",
+            serde_json::json!({
+                "rows_rolled_back": rows_rolled_back,
+                "quantity_reversed": quantity_reversed,
+                "rows_skipped": rows_skipped,
+                "action": "User rolled back imported stock"
+            }),
+            None,
+        ).await
+    }
+
     /// Generic event logger - all specific logs call this
     async fn log_event(
         &self,
@@ -122,6 +122,10 @@ impl AuthService {
             return Err(AppError::Unauthorized);
         }
 
+        crate::services::UserSuspensionService::new(self.user_repo.pool().clone())
+            .ensure_not_suspended(user.id)
+            .await?;
+
         let token = self.jwt_service.generate_token(
             user.id,
             &user.email,
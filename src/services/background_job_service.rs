@@ -0,0 +1,394 @@
+/// Background Job Service
+///
+/// Postgres-backed job queue. Jobs are enqueued with a type and a JSON payload and run by
+/// the background job worker of any instance:
+/// - claim: a worker takes the oldest due job of a type with FOR UPDATE SKIP LOCKED and
+///   holds a lease on it; jobs whose lease expired (their instance died) are claimed again
+/// - concurrency: claims of a type are serialized with an advisory lock and refused once
+///   `max_concurrency` jobs of the type hold a live lease
+/// - retries: a failed attempt is queued again after an exponential backoff until
+///   `max_attempts` attempts were made
+/// - cancellation: queued jobs are cancelled right away; running jobs finish their
+///   attempt and are then cancelled instead of retried
+/// - release: jobs interrupted by a shutdown are queued again right away, without
+///   using up an attempt
+///
+/// Per-type settings live in background_job_types.
+
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::{
+    middleware::error_handling::{AppError, Result},
+    models::background_job::*,
+};
+
+/// Longest error message stored on a job
+const MAX_ERROR_LENGTH: usize = 2000;
+
+pub struct BackgroundJobService {
+    db_pool: PgPool,
+}
+
+impl BackgroundJobService {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self { db_pool }
+    }
+
+    // ========================================================================
+    // QUEUE
+    // ========================================================================
+
+    /// Queue a job. While a job with the same dedupe key is queued or running, that job is
+    /// returned instead of queueing another one.
+    pub async fn enqueue(&self, job: NewJob) -> Result<BackgroundJob> {
+        let settings = self.settings(job.job_type).await?;
+
+        let inserted = sqlx::query_as::<_, BackgroundJob>(
+            r#"
+            INSERT INTO background_jobs (job_type, payload, dedupe_key, max_attempts, run_at, created_by)
+            VALUES ($1, $2, $3, $4, COALESCE($5, NOW()), $6)
+            ON CONFLICT (dedupe_key) WHERE dedupe_key IS NOT NULL AND status IN ('queued', 'running')
+            DO NOTHING
+            RETURNING *
+            "#,
+        )
+        .bind(job.job_type.as_str())
+        .bind(&job.payload)
+        .bind(&job.dedupe_key)
+        .bind(settings.max_attempts)
+        .bind(job.run_at)
+        .bind(job.created_by)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        if let Some(inserted) = inserted {
+            tracing::info!("Queued {} job {}", inserted.job_type, inserted.id);
+            return Ok(inserted);
+        }
+
+        let existing = sqlx::query_as::<_, BackgroundJob>(
+            "SELECT * FROM background_jobs WHERE dedupe_key = $1 AND status IN ('queued', 'running')",
+        )
+        .bind(&job.dedupe_key)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        // The other job finished between the insert and the lookup
+        match existing {
+            Some(existing) => Ok(existing),
+            None => Err(AppError::Conflict),
+        }
+    }
+
+    pub async fn settings(&self, job_type: JobType) -> Result<JobTypeSettings> {
+        sqlx::query_as::<_, JobTypeSettings>("SELECT * FROM background_job_types WHERE job_type = $1")
+            .bind(job_type.as_str())
+            .fetch_optional(&self.db_pool)
+            .await?
+            .ok_or_else(|| AppError::Internal(anyhow::anyhow!("Job type {} is not configured", job_type.as_str())))
+    }
+
+    /// Take the lease of the next due job of a type, if the type is enabled and below its
+    /// concurrency limit
+    pub async fn claim(&self, job_type: JobType, owner: &str) -> Result<Option<BackgroundJob>> {
+        let mut tx = self.db_pool.begin().await?;
+
+        // Serialize claims of this type so the running count can't be raced past the limit
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext('background_jobs:' || $1))")
+            .bind(job_type.as_str())
+            .execute(&mut *tx)
+            .await?;
+
+        let Some(settings) = sqlx::query_as::<_, JobTypeSettings>(
+            "SELECT * FROM background_job_types WHERE job_type = $1 AND is_enabled = TRUE",
+        )
+        .bind(job_type.as_str())
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        // Jobs whose worker died on their last attempt are not retried
+        sqlx::query(
+            r#"
+            UPDATE background_jobs
+            SET status = CASE WHEN cancel_requested THEN 'cancelled' ELSE 'failed' END,
+                last_error = COALESCE(last_error, 'Worker stopped before the job finished'),
+                lease_owner = NULL,
+                lease_expires_at = NULL,
+                completed_at = NOW(),
+                updated_at = NOW()
+            WHERE job_type = $1
+              AND status = 'running'
+              AND lease_expires_at < NOW()
+              AND (attempts >= max_attempts OR cancel_requested)
+            "#,
+        )
+        .bind(job_type.as_str())
+        .execute(&mut *tx)
+        .await?;
+
+        let running = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM background_jobs WHERE job_type = $1 AND status = 'running' AND lease_expires_at >= NOW()",
+        )
+        .bind(job_type.as_str())
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if running >= settings.max_concurrency as i64 {
+            return Ok(None);
+        }
+
+        let claimed = sqlx::query_as::<_, BackgroundJob>(
+            r#"
+            UPDATE background_jobs
+            SET status = 'running',
+                attempts = attempts + 1,
+                lease_owner = $2,
+                lease_expires_at = NOW() + make_interval(secs => $3),
+                started_at = NOW(),
+                updated_at = NOW()
+            WHERE id = (
+                SELECT id FROM background_jobs
+                WHERE job_type = $1
+                  AND ((status = 'queued' AND run_at <= NOW())
+                       OR (status = 'running' AND lease_expires_at < NOW()))
+                ORDER BY run_at
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING *
+            "#,
+        )
+        .bind(job_type.as_str())
+        .bind(owner)
+        .bind(settings.lease_seconds as f64)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(claimed)
+    }
+
+    /// Extend the lease of a running job by its type's lease length. Returns false when the
+    /// lease was lost to another worker.
+    pub async fn renew_lease(&self, job_id: Uuid, owner: &str) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE background_jobs
+            SET lease_expires_at = NOW() + make_interval(secs => (
+                    SELECT lease_seconds FROM background_job_types t WHERE t.job_type = background_jobs.job_type
+                )),
+                updated_at = NOW()
+            WHERE id = $1 AND lease_owner = $2 AND status = 'running'
+            "#,
+        )
+        .bind(job_id)
+        .bind(owner)
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn complete(&self, job_id: Uuid, owner: &str, result: serde_json::Value) -> Result<()> {
+        let updated = sqlx::query(
+            r#"
+            UPDATE background_jobs
+            SET status = 'succeeded',
+                result = $3,
+                last_error = NULL,
+                lease_owner = NULL,
+                lease_expires_at = NULL,
+                completed_at = NOW(),
+                updated_at = NOW()
+            WHERE id = $1 AND lease_owner = $2 AND status = 'running'
+            "#,
+        )
+        .bind(job_id)
+        .bind(owner)
+        .bind(result)
+        .execute(&self.db_pool)
+        .await?;
+
+        if updated.rows_affected() == 0 {
+            tracing::warn!("Background job {} outlived its lease and was taken over by another worker", job_id);
+        }
+        Ok(())
+    }
+
+    /// Record a failed attempt: queue the job again after a backoff, or fail it for good once
+    /// it used its attempts or was cancelled
+    pub async fn fail(&self, job: &BackgroundJob, owner: &str, error: &str) -> Result<JobStatus> {
+        let error: String = error.chars().take(MAX_ERROR_LENGTH).collect();
+        let retry_at = Utc::now() + job_retry_delay(job.attempts);
+
+        let status = sqlx::query_scalar::<_, String>(
+            r#"
+            UPDATE background_jobs
+            SET status = CASE
+                    WHEN cancel_requested THEN 'cancelled'
+                    WHEN attempts < max_attempts THEN 'queued'
+                    ELSE 'failed'
+                END,
+                run_at = $4,
+                last_error = $3,
+                lease_owner = NULL,
+                lease_expires_at = NULL,
+                completed_at = CASE WHEN cancel_requested OR attempts >= max_attempts THEN NOW() END,
+                updated_at = NOW()
+            WHERE id = $1 AND lease_owner = $2 AND status = 'running'
+            RETURNING status
+            "#,
+        )
+        .bind(job.id)
+        .bind(owner)
+        .bind(&error)
+        .bind(retry_at)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        let Some(status) = status else {
+            tracing::warn!("Background job {} outlived its lease and was taken over by another worker", job.id);
+            return Ok(JobStatus::Running);
+        };
+        Ok(JobStatus::parse(&status).unwrap_or(JobStatus::Failed))
+    }
+
+    /// Hand back a job interrupted by a shutdown: it is queued again right away and the
+    /// interrupted attempt doesn't count against `max_attempts`. Jobs asked to cancel are
+    /// cancelled instead.
+    pub async fn release(&self, job_id: Uuid, owner: &str) -> Result<JobStatus> {
+        let status = sqlx::query_scalar::<_, String>(
+            r#"
+            UPDATE background_jobs
+            SET status = CASE WHEN cancel_requested THEN 'cancelled' ELSE 'queued' END,
+                max_attempts = GREATEST(max_attempts, attempts + 1),
+                run_at = NOW(),
+                last_error = $3,
+                lease_owner = NULL,
+                lease_expires_at = NULL,
+                completed_at = CASE WHEN cancel_requested THEN NOW() END,
+                updated_at = NOW()
+            WHERE id = $1 AND lease_owner = $2 AND status = 'running'
+            RETURNING status
+            "#,
+        )
+        .bind(job_id)
+        .bind(owner)
+        .bind(crate::services::INTERRUPTED_MESSAGE)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        Ok(status.as_deref().and_then(JobStatus::parse).unwrap_or(JobStatus::Running))
+    }
+
+    // ========================================================================
+    // ADMINISTRATION
+    // ========================================================================
+
+    pub async fn list(&self, query: &BackgroundJobQuery) -> Result<Vec<BackgroundJob>> {
+        if let Some(job_type) = query.job_type.as_deref() {
+            if JobType::parse(job_type).is_none() {
+                return Err(AppError::BadRequest(format!("Unknown job type '{}'", job_type)));
+            }
+        }
+        if let Some(status) = query.status.as_deref() {
+            if JobStatus::parse(status).is_none() {
+                return Err(AppError::BadRequest(format!("Unknown job status '{}'", status)));
+            }
+        }
+
+        let jobs = sqlx::query_as::<_, BackgroundJob>(
+            r#"
+            SELECT * FROM background_jobs
+            WHERE ($1::TEXT IS NULL OR job_type = $1)
+              AND ($2::TEXT IS NULL OR status = $2)
+            ORDER BY created_at DESC
+            LIMIT $3 OFFSET $4
+            "#,
+        )
+        .bind(&query.job_type)
+        .bind(&query.status)
+        .bind(query.limit.unwrap_or(50).clamp(1, 200))
+        .bind(query.offset.unwrap_or(0).max(0))
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(jobs)
+    }
+
+    pub async fn get(&self, job_id: Uuid) -> Result<BackgroundJob> {
+        sqlx::query_as::<_, BackgroundJob>("SELECT * FROM background_jobs WHERE id = $1")
+            .bind(job_id)
+            .fetch_optional(&self.db_pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Background job not found".to_string()))
+    }
+
+    /// Queue a failed or cancelled job again, with one more attempt
+    pub async fn retry(&self, job_id: Uuid, run_at: Option<DateTime<Utc>>) -> Result<BackgroundJob> {
+        let job = self.get(job_id).await?;
+        if !matches!(job.status.as_str(), "failed" | "cancelled") {
+            return Err(AppError::BadRequest(format!(
+                "Only failed or cancelled jobs can be retried (status: {})",
+                job.status
+            )));
+        }
+
+        // A retried job takes its dedupe key back, so it can't run next to a newer job
+        let retried = sqlx::query_as::<_, BackgroundJob>(
+            r#"
+            UPDATE background_jobs
+            SET status = 'queued',
+                max_attempts = GREATEST(max_attempts, attempts + 1),
+                run_at = COALESCE($2, NOW()),
+                cancel_requested = FALSE,
+                completed_at = NULL,
+                updated_at = NOW()
+            WHERE id = $1 AND status IN ('failed', 'cancelled')
+              AND NOT EXISTS (
+                  SELECT 1 FROM background_jobs other
+                  WHERE other.dedupe_key = background_jobs.dedupe_key
+                    AND other.id <> background_jobs.id
+                    AND other.status IN ('queued', 'running')
+              )
+            RETURNING *
+            "#,
+        )
+        .bind(job_id)
+        .bind(run_at)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        retried.ok_or(AppError::Conflict)
+    }
+
+    /// Cancel a queued job, or ask a running job to stop retrying
+    pub async fn cancel(&self, job_id: Uuid) -> Result<BackgroundJob> {
+        let cancelled = sqlx::query_as::<_, BackgroundJob>(
+            r#"
+            UPDATE background_jobs
+            SET status = CASE WHEN status = 'queued' THEN 'cancelled' ELSE status END,
+                completed_at = CASE WHEN status = 'queued' THEN NOW() ELSE completed_at END,
+                cancel_requested = TRUE,
+                updated_at = NOW()
+            WHERE id = $1 AND status IN ('queued', 'running')
+            RETURNING *
+            "#,
+        )
+        .bind(job_id)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        match cancelled {
+            Some(job) => Ok(job),
+            None => {
+                let job = self.get(job_id).await?;
+                Err(AppError::BadRequest(format!("Job has already finished (status: {})", job.status)))
+            }
+        }
+    }
+}
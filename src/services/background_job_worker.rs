@@ -0,0 +1,422 @@
+/// Background Job Worker
+///
+/// Runs the jobs of the background job queue. Every instance runs a worker with one loop
+/// per job type; a loop claims due jobs until its type reaches its concurrency limit and
+/// runs each in its own task, renewing the job's lease until the job finishes.
+///
+/// Job types:
+/// - erp_sync: manual sync of an ERP connection
+/// - ai_import: inventory rows of an AI import session with its approved mapping
+/// - openfda_sync: OpenFDA drug catalog sync, new or resumed from its checkpoint
+/// - document_batch: regulatory document batch
+/// - sanctions_list_sync: manual sanctions list sync
+/// - data_export: export file of inventory, transactions, audit logs or a catalog
+/// - encryption_reencrypt: re-encryption of encrypted columns with the active key
+/// - batch_operation: operations submitted together through the batch API
+/// - openfda_device_sync, openfda_recall_sync: OpenFDA device and recall syncs
+/// - ema_sync: EMA ePI catalog sync
+/// - regulator_catalog_sync: regulator catalog sync of one source
+///
+/// Every job type can run more than once for the same payload (after a failure or when
+/// an instance dies mid-run), so each continues from the progress it persisted. When a
+/// job gives up, the record it worked on is marked failed. Jobs that fail while the
+/// instance shuts down are released to the queue instead (see `Shutdown`).
+
+use chrono::Utc;
+use serde_json::json;
+use std::time::Duration;
+use uuid::Uuid;
+use crate::{
+    config::AppConfig,
+    middleware::error_handling::{AppError, Result},
+    models::{ai_import::ColumnMapping, background_job::*},
+    services::{
+        comprehensive_audit_service::{ActionResult, AuditLogEntry, ComprehensiveAuditService, EventCategory, Severity},
+        erp::{ErpConnectionService, ErpSyncService, SyncDirection},
+        regulator_catalogs::RegulatorCatalogService,
+        AiImportService, BackgroundJobService, BatchImportProcessor, BatchOperationService, DataExportService, DocumentBatchService,
+        EmaService, EncryptionReencryptionService, FileParserService, InFlight, OpenFdaDeviceService, OpenFdaRecallService,
+        OpenFdaService, SanctionsListService, Shutdown, INTERRUPTED_MESSAGE,
+    },
+    utils::encrypted_file_storage::EncryptedFileStorage,
+};
+
+/// How often each job type loop looks for due jobs
+const JOB_POLL_SECONDS: u64 = 5;
+
+/// How often a running job renews its lease (leases are at least 30 seconds)
+const LEASE_RENEW_SECONDS: u64 = 10;
+
+#[derive(Clone)]
+pub struct BackgroundJobWorker {
+    config: AppConfig,
+    /// Lease owner id of this instance
+    instance_id: String,
+}
+
+impl BackgroundJobWorker {
+    pub fn new(config: AppConfig) -> Self {
+        let instance_id = format!(
+            "{}-{}-{}",
+            std::env::var("HOSTNAME").unwrap_or_else(|_| "atlas".to_string()),
+            std::process::id(),
+            &Uuid::new_v4().simple().to_string()[..8]
+        );
+
+        Self { config, instance_id }
+    }
+
+    /// Run the loops of all job types until the process exits
+    pub async fn run(&self) {
+        tracing::info!("Background job worker running as {}", self.instance_id);
+
+        futures::future::join_all(JobType::ALL.iter().map(|job_type| self.run_type_loop(*job_type))).await;
+    }
+
+    async fn run_type_loop(&self, job_type: JobType) {
+        let queue = BackgroundJobService::new(self.config.database_pool.clone());
+        let mut ticker = tokio::time::interval(Duration::from_secs(JOB_POLL_SECONDS));
+
+        loop {
+            ticker.tick().await;
+            // Jobs already running finish; new ones wait until maintenance ends
+            if crate::services::MaintenanceService::pauses("background_jobs") {
+                continue;
+            }
+
+            // Claim until nothing is due or the type is at its concurrency limit
+            loop {
+                match queue.claim(job_type, &self.instance_id).await {
+                    Ok(Some(job)) => {
+                        let worker = self.clone();
+                        tokio::spawn(async move { worker.execute(job_type, job).await });
+                    }
+                    Ok(None) => break,
+                    Err(e) => {
+                        tracing::error!("Failed to claim {} job: {}", job_type.as_str(), e);
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    async fn execute(&self, job_type: JobType, job: BackgroundJob) {
+        let queue = BackgroundJobService::new(self.config.database_pool.clone());
+        tracing::info!(
+            "Running {} job {} (attempt {}/{})",
+            job.job_type,
+            job.id,
+            job.attempts,
+            job.max_attempts
+        );
+
+        let _in_flight = Shutdown::track(InFlight::Job { job_id: job.id, lease_owner: self.instance_id.clone() });
+        let run = self.dispatch(job_type, &job);
+        tokio::pin!(run);
+        let mut renew = tokio::time::interval(Duration::from_secs(LEASE_RENEW_SECONDS));
+        renew.tick().await;
+
+        let result = loop {
+            tokio::select! {
+                result = &mut run => break result,
+                _ = renew.tick() => match queue.renew_lease(job.id, &self.instance_id).await {
+                    Ok(true) => {}
+                    Ok(false) => tracing::warn!("Background job {} lost its lease", job.id),
+                    Err(e) => tracing::warn!("Failed to renew the lease of background job {}: {}", job.id, e),
+                },
+            }
+        };
+
+        match result {
+            Ok(output) => {
+                if let Err(e) = queue.complete(job.id, &self.instance_id, output).await {
+                    tracing::error!("Failed to complete background job {}: {}", job.id, e);
+                }
+            }
+            // Most likely stopped by the shutdown: another instance picks the job up again
+            Err(e) if Shutdown::requested() => match queue.release(job.id, &self.instance_id).await {
+                Ok(JobStatus::Queued) => {
+                    tracing::warn!("{} job {} interrupted by shutdown, queued again: {}", job.job_type, job.id, e);
+                }
+                Ok(JobStatus::Cancelled) => {
+                    if let Err(e) = self.give_up(job_type, &job, INTERRUPTED_MESSAGE).await {
+                        tracing::error!("Failed to mark the work of background job {} as failed: {}", job.id, e);
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => tracing::error!("Failed to release background job {}: {}", job.id, e),
+            },
+            Err(e) => {
+                let error = e.to_string();
+                match queue.fail(&job, &self.instance_id, &error).await {
+                    Ok(JobStatus::Queued) => {
+                        tracing::warn!("{} job {} failed, will retry: {}", job.job_type, job.id, error);
+                    }
+                    Ok(JobStatus::Failed) | Ok(JobStatus::Cancelled) => {
+                        tracing::error!("{} job {} gave up after {} attempts: {}", job.job_type, job.id, job.attempts, error);
+                        if let Err(e) = self.give_up(job_type, &job, &error).await {
+                            tracing::error!("Failed to mark the work of background job {} as failed: {}", job.id, e);
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e) => tracing::error!("Failed to record failure of background job {}: {}", job.id, e),
+                }
+            }
+        }
+    }
+
+    async fn dispatch(&self, job_type: JobType, job: &BackgroundJob) -> Result<serde_json::Value> {
+        match job_type {
+            JobType::ErpSync => self.run_erp_sync(job, job.payload()?).await,
+            JobType::AiImport => self.run_ai_import(job.payload()?).await,
+            JobType::OpenFdaSync => {
+                let payload: OpenFdaSyncJob = job.payload()?;
+                OpenFdaService::run_sync_job(&payload, job.is_retry(), self.config.database_pool.clone()).await?;
+                Ok(json!({ "sync_log_id": payload.sync_log_id }))
+            }
+            JobType::OpenFdaDeviceSync => {
+                let payload: OpenFdaDeviceSyncJob = job.payload()?;
+                OpenFdaDeviceService::run_sync_job(&payload, job.is_retry(), self.config.database_pool.clone()).await?;
+                Ok(json!({ "sync_log_id": payload.sync_log_id }))
+            }
+            JobType::OpenFdaRecallSync => {
+                let payload: OpenFdaRecallSyncJob = job.payload()?;
+                OpenFdaRecallService::run_sync_job(&payload, job.is_retry(), self.config.database_pool.clone()).await?;
+                Ok(json!({ "sync_log_id": payload.sync_log_id }))
+            }
+            JobType::EmaSync => {
+                let payload: EmaSyncJob = job.payload()?;
+                EmaService::run_sync_job(&payload, job.is_retry(), self.config.database_pool.clone()).await?;
+                Ok(json!({ "sync_log_id": payload.sync_log_id }))
+            }
+            JobType::RegulatorCatalogSync => {
+                let payload: RegulatorCatalogSyncJob = job.payload()?;
+                RegulatorCatalogService::run_sync_job(&payload, job.is_retry(), self.config.database_pool.clone()).await?;
+                Ok(json!({ "sync_log_id": payload.sync_log_id, "source": payload.source }))
+            }
+            JobType::DocumentBatch => {
+                let payload: DocumentBatchJob = job.payload()?;
+                let batch = DocumentBatchService::new(self.config.database_pool.clone(), &self.config.encryption_key)
+                    .run(payload.batch_id)
+                    .await?;
+                Ok(json!({
+                    "batch_id": batch.id,
+                    "status": batch.status,
+                    "succeeded_items": batch.succeeded_items,
+                    "failed_items": batch.failed_items,
+                }))
+            }
+            JobType::SanctionsListSync => {
+                let payload: SanctionsListSyncJob = job.payload()?;
+                let service = SanctionsListService::new(self.config.database_pool.clone());
+                let entries = match payload.source {
+                    Some(source) => service.sync(source, Some(payload.requested_by)).await?.entry_count.unwrap_or(0) as i64,
+                    None => service.sync_all(Some(payload.requested_by)).await?,
+                };
+                Ok(json!({ "entries": entries }))
+            }
+            JobType::DataExport => {
+                let payload: DataExportJob = job.payload()?;
+                let export = self.data_exports().run(payload.export_id).await?;
+                Ok(json!({
+                    "export_id": export.id,
+                    "rows_written": export.rows_written,
+                    "file_size_bytes": export.file_size_bytes,
+                }))
+            }
+            JobType::EncryptionReencrypt => {
+                EncryptionReencryptionService::new(self.config.database_pool.clone(), &self.config.encryption_key)
+                    .run()
+                    .await
+            }
+            JobType::BatchOperation => {
+                let payload: BatchOperationJob = job.payload()?;
+                let batch = BatchOperationService::new(self.config.database_pool.clone(), &self.config.encryption_key)
+                    .run(payload.batch_id)
+                    .await?;
+                Ok(json!({
+                    "batch_id": batch.id,
+                    "status": batch.status,
+                    "succeeded_items": batch.succeeded_items,
+                    "failed_items": batch.failed_items,
+                }))
+            }
+        }
+    }
+
+    fn data_exports(&self) -> DataExportService {
+        DataExportService::new(
+            self.config.database_pool.clone(),
+            &self.config.file_storage_path,
+            &self.config.encryption_key,
+        )
+    }
+
+    /// Mark the record a job worked on as failed once the job stops retrying
+    async fn give_up(&self, job_type: JobType, job: &BackgroundJob, error: &str) -> Result<()> {
+        match job_type {
+            JobType::AiImport => {
+                let payload: AiImportJob = job.payload()?;
+                sqlx::query(
+                    "UPDATE ai_import_sessions SET status = 'failed', error_message = $2 WHERE id = $1 AND status = 'importing'",
+                )
+                .bind(payload.session_id)
+                .bind(error)
+                .execute(&self.config.database_pool)
+                .await?;
+            }
+            JobType::DocumentBatch => {
+                let payload: DocumentBatchJob = job.payload()?;
+                DocumentBatchService::new(self.config.database_pool.clone(), &self.config.encryption_key)
+                    .fail(payload.batch_id, error)
+                    .await?;
+            }
+            JobType::DataExport => {
+                let payload: DataExportJob = job.payload()?;
+                self.data_exports().fail(payload.export_id, error).await?;
+            }
+            JobType::BatchOperation => {
+                let payload: BatchOperationJob = job.payload()?;
+                BatchOperationService::new(self.config.database_pool.clone(), &self.config.encryption_key)
+                    .fail(payload.batch_id, error)
+                    .await?;
+            }
+            // Failed ERP and catalog syncs record their outcome themselves; sanctions list
+            // syncs and re-encryption have nothing to release
+            JobType::ErpSync
+            | JobType::OpenFdaSync
+            | JobType::OpenFdaDeviceSync
+            | JobType::OpenFdaRecallSync
+            | JobType::EmaSync
+            | JobType::RegulatorCatalogSync
+            | JobType::SanctionsListSync
+            | JobType::EncryptionReencrypt => {}
+        }
+        Ok(())
+    }
+
+    // ========================================================================
+    // ERP SYNC
+    // ========================================================================
+
+    async fn run_erp_sync(&self, job: &BackgroundJob, payload: ErpSyncJob) -> Result<serde_json::Value> {
+        let pool = self.config.database_pool.clone();
+        let connection_id = payload.connection_id;
+        let direction = match payload.direction.as_str() {
+            "atlas_to_erp" => SyncDirection::AtlasToErp,
+            "erp_to_atlas" => SyncDirection::ErpToAtlas,
+            "bidirectional" => SyncDirection::Bidirectional,
+            other => return Err(AppError::BadRequest(format!("Invalid sync direction: {}", other))),
+        };
+
+        // The handler claimed the connection for the first attempt; the previous attempt
+        // released it
+        if job.is_retry() {
+            let claimed = ErpConnectionService::new(pool.clone())
+                .try_start_sync(connection_id)
+                .await
+                .map_err(|e| AppError::Internal(anyhow::anyhow!(e.to_string())))?;
+            if !claimed {
+                return Err(AppError::Internal(anyhow::anyhow!(
+                    "Connection {} is still syncing",
+                    connection_id
+                )));
+            }
+        }
+
+        let sync_service = ErpSyncService::new(pool.clone());
+        let start_time = Utc::now();
+        let result = sync_service.sync_in_direction(connection_id, direction, "user_manual").await;
+
+        match &result {
+            Ok(sync_result) => tracing::info!(
+                "Sync completed for connection {}: {} synced, {} failed",
+                connection_id,
+                sync_result.items_synced,
+                sync_result.items_failed
+            ),
+            Err(e) => tracing::error!("Sync failed for connection {}: {}", connection_id, e),
+        }
+
+        // Release the connection and update its sync metadata
+        if let Err(e) = sync_service.finish_connection_sync(connection_id, &result, start_time).await {
+            tracing::error!("Failed to record sync outcome for connection {}: {}", connection_id, e);
+        }
+
+        ComprehensiveAuditService::new(pool)
+            .log(AuditLogEntry {
+                event_type: "erp_manual_sync_completed".to_string(),
+                event_category: EventCategory::System,
+                severity: if result.is_ok() { Severity::Info } else { Severity::Warning },
+                actor_user_id: Some(payload.user_id),
+                actor_type: "user".to_string(),
+                resource_type: Some("erp_sync".to_string()),
+                resource_id: Some(connection_id.to_string()),
+                action: "manual_sync".to_string(),
+                action_result: if result.is_ok() { ActionResult::Success } else { ActionResult::Failure },
+                event_data: json!({ "job_id": job.id, "attempt": job.attempts }),
+                ..Default::default()
+            })
+            .await
+            .ok();
+
+        let sync_result = result.map_err(|e| AppError::Internal(anyhow::anyhow!(e.to_string())))?;
+        Ok(json!({
+            "items_synced": sync_result.items_synced,
+            "items_failed": sync_result.items_failed,
+            "items_skipped": sync_result.items_skipped,
+        }))
+    }
+
+    // ========================================================================
+    // AI IMPORT
+    // ========================================================================
+
+    async fn run_ai_import(&self, payload: AiImportJob) -> Result<serde_json::Value> {
+        let pool = self.config.database_pool.clone();
+        let session_id = payload.session_id;
+
+        // Only reading the session and its review decisions; no Claude calls are made
+        let ai_service = AiImportService::new(pool.clone(), std::env::var("ANTHROPIC_API_KEY").unwrap_or_default());
+        let session = ai_service.get_session(session_id).await?;
+        if session.status != "importing" {
+            tracing::info!("Import session {} is {}, nothing to import", session_id, session.status);
+            return Ok(json!({ "session_status": session.status }));
+        }
+
+        let mapping: ColumnMapping = serde_json::from_value(
+            session.ai_mapping.ok_or_else(|| AppError::BadRequest("No mapping available for this session".to_string()))?,
+        )?;
+        let file_path = session
+            .file_path
+            .ok_or_else(|| AppError::BadRequest("No file available for this session".to_string()))?;
+
+        let file_storage = EncryptedFileStorage::new(&self.config.file_storage_path, &self.config.encryption_key)?;
+        let file_data = file_storage.read_encrypted_file(&file_path)?;
+        let parsed_file = FileParserService::parse(&file_data, &session.original_filename)?;
+
+        // Rows rejected during review are skipped; the rest commit even if some fail
+        let rejected_rows = ai_service.rejected_rows(session_id).await?;
+
+        let stats = BatchImportProcessor::new(pool)
+            .process_import(session_id, payload.user_id, parsed_file, mapping, &rejected_rows)
+            .await?;
+
+        tracing::info!(
+            "Import completed for session {}: {} imported, {} failed, {} rejected",
+            session_id,
+            stats.rows_imported,
+            stats.rows_failed,
+            stats.rows_rejected
+        );
+
+        Ok(json!({
+            "rows_processed": stats.rows_processed,
+            "rows_imported": stats.rows_imported,
+            "rows_failed": stats.rows_failed,
+            "rows_flagged": stats.rows_flagged,
+            "rows_rejected": stats.rows_rejected,
+        }))
+    }
+}
@@ -10,7 +10,9 @@ use crate::services::file_parser_service::{FileParserService, ParsedFile};
 use crate::repositories::{InventoryRepository, PharmaceuticalRepository};
 use crate::models::inventory::CreateInventoryRequest;
 use crate::models::pharmaceutical::CreatePharmaceuticalRequest;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use sqlx::Connection;
 use tokio::sync::Semaphore;
 
 const MAX_CONCURRENT_VALIDATIONS: usize = 10;
@@ -33,13 +35,17 @@ impl BatchImportProcessor {
         }
     }
 
-    /// Main import orchestration - processes entire file
+    /// Main import orchestration - processes entire file.
+    /// Rows in `rejected_rows` (1-based) are recorded as rejected and not imported.
+    /// Batches commit atomically, so a retried import skips the batches an interrupted
+    /// run already committed and only counts their results.
     pub async fn process_import(
         &self,
         session_id: Uuid,
         user_id: Uuid,
         parsed_file: ParsedFile,
         mapping: ColumnMapping,
+        rejected_rows: &HashSet<usize>,
     ) -> Result<ImportStats> {
         tracing::info!("Starting batch import for session: {}", session_id);
 
@@ -48,10 +54,20 @@ impl BatchImportProcessor {
 
         let total_rows = parsed_file.rows.len();
         let mut stats = ImportStats::default();
+        let mut committed = self.committed_batches(session_id).await?;
 
         // Process in batches for better performance
         for (batch_idx, chunk) in parsed_file.rows.chunks(BATCH_SIZE).enumerate() {
             let batch_start_row = batch_idx * BATCH_SIZE;
+
+            if let Some(mut batch_stats) = committed.remove(&batch_idx) {
+                // Rows that failed validation have no stored result
+                batch_stats.rows_failed += chunk.len().saturating_sub(batch_stats.rows_processed);
+                batch_stats.rows_processed = chunk.len();
+                stats.merge(batch_stats);
+                tracing::info!("Skipping batch {} of session {}: committed by an earlier run", batch_idx + 1, session_id);
+                continue;
+            }
             
             tracing::info!(
                 "Processing batch {}/{} (rows {}-{})",
@@ -68,6 +84,7 @@ impl BatchImportProcessor {
                 chunk,
                 &mapping,
                 batch_start_row,
+                rejected_rows,
             ).await?;
 
             stats.merge(batch_stats);
@@ -90,7 +107,9 @@ impl BatchImportProcessor {
         Ok(stats)
     }
 
-    /// Process a single batch of rows with transaction safety
+    /// Process a single batch of rows with transaction safety.
+    /// Each row is written under a savepoint, so a row that fails to insert is
+    /// recorded as failed without discarding the rest of the batch.
     async fn process_batch(
         &self,
         session_id: Uuid,
@@ -99,12 +118,23 @@ impl BatchImportProcessor {
         rows: &[Vec<String>],
         mapping: &ColumnMapping,
         batch_offset: usize,
+        rejected_rows: &HashSet<usize>,
     ) -> Result<ImportStats> {
         let mut stats = ImportStats::default();
 
         // Start transaction for the entire batch
         let mut tx = self.db_pool.begin().await?;
 
+        // Rows rejected during review are recorded but never validated or imported
+        for (idx, row_data) in rows.iter().enumerate() {
+            let row_number = batch_offset + idx + 1;
+            if rejected_rows.contains(&row_number) {
+                Self::save_rejected_row_tx(&mut tx, session_id, row_number as i32, row_data).await?;
+                stats.rows_processed += 1;
+                stats.rows_rejected += 1;
+            }
+        }
+
         // Semaphore for controlling concurrency (validation only, not DB writes)
         let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT_VALIDATIONS));
 
@@ -113,6 +143,9 @@ impl BatchImportProcessor {
 
         for (idx, row_data) in rows.iter().enumerate() {
             let row_number = batch_offset + idx + 1;
+            if rejected_rows.contains(&row_number) {
+                continue;
+            }
             let permit = semaphore.clone().acquire_owned().await.unwrap();
 
             let validator = InventoryValidatorService::new(self.db_pool.clone());
@@ -173,31 +206,37 @@ impl BatchImportProcessor {
         for (row_number, mapped_row, validation, row_data) in validated_rows {
             stats.rows_processed += 1;
 
-            let row_status = if !validation.is_valid {
+            let mut row_status = if !validation.is_valid {
                 "failed"
             } else if !validation.warnings.is_empty() {
                 "flagged_for_review"
             } else {
                 "imported"
             };
+            let mut error_message = None;
 
             // Create inventory if valid
             let (inventory_id, pharma_id) = if validation.is_valid {
+                let mut savepoint = tx.begin().await?;
                 match self.create_inventory_from_row_tx(
-                    &mut tx,
+                    &mut savepoint,
                     &mapped_row,
                     &validation,
                     user_id,
                 ).await {
                     Ok(ids) => {
+                        savepoint.commit().await?;
                         stats.rows_imported += 1;
                         ids
                     }
                     Err(e) => {
                         tracing::error!("Failed to create inventory for row {}: {}", row_number, e);
-                        // Rollback transaction on error
-                        tx.rollback().await?;
-                        return Err(e);
+                        // Undo this row only; the rest of the batch still commits
+                        savepoint.rollback().await?;
+                        stats.rows_failed += 1;
+                        row_status = "failed";
+                        error_message = Some(e.to_string());
+                        (None, None)
                     }
                 }
             } else {
@@ -219,10 +258,10 @@ impl BatchImportProcessor {
                 row_status,
                 inventory_id,
                 pharma_id,
+                error_message.as_deref(),
             ).await?;
         }
 
-        // Commit transaction - all or nothing
         tx.commit().await?;
 
         tracing::info!("Batch committed: {} rows processed", stats.rows_processed);
@@ -268,6 +307,10 @@ impl BatchImportProcessor {
                         strength: row.strength.clone(),
                         dosage_form: row.dosage_form.clone(),
                         storage_requirements: None,
+                        product_domain: None,
+                        udi_di: None,
+                        device_class: None,
+                        target_species: None,
                     };
 
                     pharma_repo.create(&pharma_request).await?.id
@@ -288,6 +331,10 @@ impl BatchImportProcessor {
                 strength: row.strength.clone(),
                 dosage_form: row.dosage_form.clone(),
                 storage_requirements: None,
+                product_domain: None,
+                udi_di: None,
+                device_class: None,
+                target_species: None,
             };
 
             pharma_repo.create(&pharma_request).await?.id
@@ -367,6 +414,45 @@ impl BatchImportProcessor {
         Ok(())
     }
 
+    /// Stats of the batches an earlier run of the session committed, by batch index
+    async fn committed_batches(&self, session_id: Uuid) -> Result<HashMap<usize, ImportStats>> {
+        let rows = sqlx::query_as::<_, (i32, String, bool, bool, bool)>(
+            r#"
+            SELECT row_number,
+                   status,
+                   created_inventory_id IS NOT NULL,
+                   COALESCE(cardinality(validation_warnings), 0) > 0,
+                   error_message IS NOT NULL
+            FROM ai_import_row_results
+            WHERE session_id = $1
+            "#
+        )
+        .bind(session_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let mut batches: HashMap<usize, ImportStats> = HashMap::new();
+        for (row_number, status, imported, flagged, errored) in rows {
+            let batch = batches.entry((row_number.max(1) as usize - 1) / BATCH_SIZE).or_default();
+            batch.rows_processed += 1;
+            if status == "rejected" {
+                batch.rows_rejected += 1;
+                continue;
+            }
+            if imported {
+                batch.rows_imported += 1;
+            }
+            if errored {
+                batch.rows_failed += 1;
+            }
+            if flagged {
+                batch.rows_flagged += 1;
+            }
+        }
+
+        Ok(batches)
+    }
+
     async fn update_session_status(&self, session_id: Uuid, status: &str) -> Result<()> {
         sqlx::query!(
             "UPDATE ai_import_sessions SET status = $1, import_started_at = NOW() WHERE id = $2",
@@ -401,12 +487,14 @@ impl BatchImportProcessor {
                    rows_imported = $2, 
                    rows_failed = $3,
                    rows_flagged_for_review = $4,
+                   rows_rejected = $5,
                    import_completed_at = NOW() 
-               WHERE id = $5"#,
+               WHERE id = $6"#,
             stats.rows_processed as i32,
             stats.rows_imported as i32,
             stats.rows_failed as i32,
             stats.rows_flagged as i32,
+            stats.rows_rejected as i32,
             session_id
         )
         .execute(&self.db_pool)
@@ -542,6 +630,7 @@ impl BatchImportProcessor {
         status: &str,
         inventory_id: Option<Uuid>,
         pharma_id: Option<Uuid>,
+        error_message: Option<&str>,
     ) -> Result<()> {
         let source_json = serde_json::json!(source_data);
         let mapped_json = serde_json::to_value(mapped_row).ok();
@@ -565,8 +654,8 @@ impl BatchImportProcessor {
                 mapped_data, validation_errors, validation_warnings,
                 matched_ndc, openfda_match_confidence, openfda_enriched_fields,
                 created_inventory_id, created_pharmaceutical_id,
-                processed_at
-            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, NOW())
+                error_message, processed_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, NOW())
             "#,
             Uuid::new_v4(),
             session_id,
@@ -581,7 +670,30 @@ impl BatchImportProcessor {
             enriched.flatten(),
             inventory_id,
             pharma_id,
+            error_message,
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Record a row the reviewer rejected before import
+    async fn save_rejected_row_tx(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        session_id: Uuid,
+        row_number: i32,
+        source_data: &[String],
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO ai_import_row_results (session_id, row_number, source_data, status, processed_at)
+            VALUES ($1, $2, $3, 'rejected', NOW())
+            "#
         )
+        .bind(session_id)
+        .bind(row_number)
+        .bind(serde_json::json!(source_data))
         .execute(&mut **tx)
         .await?;
 
@@ -595,6 +707,7 @@ pub struct ImportStats {
     pub rows_imported: usize,
     pub rows_failed: usize,
     pub rows_flagged: usize,
+    pub rows_rejected: usize,
 }
 
 impl ImportStats {
@@ -603,6 +716,7 @@ impl ImportStats {
         self.rows_imported += other.rows_imported;
         self.rows_failed += other.rows_failed;
         self.rows_flagged += other.rows_flagged;
+        self.rows_rejected += other.rows_rejected;
     }
 }
 
@@ -0,0 +1,307 @@
+/// Batch Operation Service
+///
+/// Runs up to MAX_BATCH_OPERATIONS operations of one kind for a user in a background job
+/// instead of one request each:
+/// - create_inquiries: place_inquiry, with the marketplace's regulatory checks and seller
+///   notifications
+/// - update_inventory: InventoryService::update_inventory, once the user may list
+/// - dismiss_notifications: NotificationService::dismiss_notification
+///
+/// Every operation goes through the same service call as its single-item endpoint and
+/// commits on its own; its result or error is recorded on its item, so one bad operation
+/// doesn't stop the batch. An operation interrupted before its result was recorded runs
+/// again on retry (an inquiry placed twice then fails with a conflict).
+
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::{
+    middleware::error_handling::{AppError, Result},
+    models::batch_operation::*,
+    repositories::{InventoryRepository, PharmaceuticalRepository},
+    services::{place_inquiry, InventoryService, ListingRightsService, NotificationService},
+};
+
+/// Unfinished batches a user may have at once
+const MAX_ACTIVE_BATCHES: i64 = 5;
+
+pub struct BatchOperationService {
+    db_pool: PgPool,
+    encryption_key: String,
+}
+
+impl BatchOperationService {
+    pub fn new(db_pool: PgPool, encryption_key: &str) -> Self {
+        Self { db_pool, encryption_key: encryption_key.to_string() }
+    }
+
+    /// Create a batch of the user's operations; run it with `run`
+    pub async fn create(&self, request: &CreateBatchOperationRequest, user_id: Uuid) -> Result<BatchOperation> {
+        let items = request.check_items().map_err(AppError::BadRequest)?;
+
+        let active: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM batch_operations WHERE user_id = $1 AND status IN ('queued', 'running')"
+        )
+        .bind(user_id)
+        .fetch_one(&self.db_pool)
+        .await?;
+        if active >= MAX_ACTIVE_BATCHES {
+            return Err(AppError::TooManyRequests(format!(
+                "At most {} batches can run at once; wait for one to finish",
+                MAX_ACTIVE_BATCHES
+            )));
+        }
+
+        let mut tx = self.db_pool.begin().await?;
+        let batch = sqlx::query_as::<_, BatchOperation>(
+            r#"
+            INSERT INTO batch_operations (user_id, operation, total_items)
+            VALUES ($1, $2, $3)
+            RETURNING *
+            "#
+        )
+        .bind(user_id)
+        .bind(request.kind().as_str())
+        .bind(items.len() as i32)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO batch_operation_items (batch_id, position, input)
+            SELECT $1, t.position::INTEGER - 1, t.input
+            FROM UNNEST($2::JSONB[]) WITH ORDINALITY AS t(input, position)
+            "#
+        )
+        .bind(batch.id)
+        .bind(&items)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(batch)
+    }
+
+    /// Record the background job that runs the batch
+    pub async fn set_job(&self, batch_id: Uuid, job_id: Uuid) -> Result<BatchOperation> {
+        let batch = sqlx::query_as::<_, BatchOperation>(
+            "UPDATE batch_operations SET job_id = $2, updated_at = NOW() WHERE id = $1 RETURNING *"
+        )
+        .bind(batch_id)
+        .bind(job_id)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        Ok(batch)
+    }
+
+    /// Run the pending operations of a batch. A batch left running by an interrupted job
+    /// continues with the operations it had not recorded.
+    pub async fn run(&self, batch_id: Uuid) -> Result<BatchOperation> {
+        let batch = sqlx::query_as::<_, BatchOperation>(
+            r#"
+            UPDATE batch_operations
+            SET status = 'running', started_at = COALESCE(started_at, NOW()), updated_at = NOW()
+            WHERE id = $1 AND status IN ('queued', 'running')
+            RETURNING *
+            "#
+        )
+        .bind(batch_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::BadRequest("Batch has already finished".to_string()))?;
+
+        let kind = BatchOperationKind::parse(&batch.operation)
+            .ok_or_else(|| AppError::Internal(anyhow::anyhow!("Unknown batch operation {}", batch.operation)))?;
+
+        let result = self.process_items(&batch, kind).await;
+        let (status, error) = match result {
+            Ok(()) => {
+                let (succeeded, failed): (i32, i32) = sqlx::query_as(
+                    "SELECT succeeded_items, failed_items FROM batch_operations WHERE id = $1"
+                )
+                .bind(batch_id)
+                .fetch_one(&self.db_pool)
+                .await?;
+                (BatchOperationStatus::finished(succeeded, failed), None)
+            }
+            Err(e) => {
+                tracing::error!("Batch operation {} failed: {}", batch_id, e);
+                (BatchOperationStatus::Failed, Some(e.to_string()))
+            }
+        };
+
+        let finished = sqlx::query_as::<_, BatchOperation>(
+            r#"
+            UPDATE batch_operations
+            SET status = $2, error = $3, completed_at = NOW(), updated_at = NOW()
+            WHERE id = $1
+            RETURNING *
+            "#
+        )
+        .bind(batch_id)
+        .bind(status.as_str())
+        .bind(error)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        tracing::info!(
+            "Batch operation {} ({}) {}: {} succeeded, {} failed",
+            batch_id,
+            finished.operation,
+            finished.status,
+            finished.succeeded_items,
+            finished.failed_items
+        );
+
+        Ok(finished)
+    }
+
+    pub async fn get(&self, batch_id: Uuid, user_id: Uuid) -> Result<BatchOperation> {
+        sqlx::query_as::<_, BatchOperation>(
+            "SELECT * FROM batch_operations WHERE id = $1 AND user_id = $2"
+        )
+        .bind(batch_id)
+        .bind(user_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Batch not found".to_string()))
+    }
+
+    /// The batch with the results of its operations so far, in submission order
+    pub async fn results(&self, batch_id: Uuid, user_id: Uuid, status: Option<&str>) -> Result<BatchOperationResults> {
+        if let Some(status) = status {
+            if !matches!(status, "pending" | "succeeded" | "failed") {
+                return Err(AppError::BadRequest(format!("Unknown item status '{}'", status)));
+            }
+        }
+
+        let batch = self.get(batch_id, user_id).await?;
+        let items = sqlx::query_as::<_, BatchOperationItem>(
+            r#"
+            SELECT * FROM batch_operation_items
+            WHERE batch_id = $1 AND ($2::VARCHAR IS NULL OR status = $2)
+            ORDER BY position
+            "#
+        )
+        .bind(batch_id)
+        .bind(status)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(BatchOperationResults { batch, items })
+    }
+
+    /// Fail a batch whose job gave up or could not be queued; operations that already ran
+    /// keep their results
+    pub async fn fail(&self, batch_id: Uuid, error: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE batch_operations
+            SET status = 'failed', error = $2, completed_at = NOW(), updated_at = NOW()
+            WHERE id = $1 AND status IN ('queued', 'running')
+            "#
+        )
+        .bind(batch_id)
+        .bind(error)
+        .execute(&self.db_pool)
+        .await?;
+        Ok(())
+    }
+
+    // ========================================================================
+    // PRIVATE HELPERS
+    // ========================================================================
+
+    async fn process_items(&self, batch: &BatchOperation, kind: BatchOperationKind) -> Result<()> {
+        let pending = sqlx::query_as::<_, BatchOperationItem>(
+            "SELECT * FROM batch_operation_items WHERE batch_id = $1 AND status = 'pending' ORDER BY position"
+        )
+        .bind(batch.id)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        for item in pending {
+            let (status, result, error, error_code) = match self.apply(kind, &item.input, batch.user_id).await {
+                Ok(result) => ("succeeded", Some(result), None, None),
+                Err(e) => {
+                    tracing::warn!(
+                        "Batch {}: {} operation {} failed: {}",
+                        batch.id,
+                        kind.as_str(),
+                        item.position,
+                        e
+                    );
+                    // Only what the API would tell the client; internal errors are logged
+                    let (_, message) = e.client_error();
+                    ("failed", None, Some(message), Some(e.code()))
+                }
+            };
+
+            let mut tx = self.db_pool.begin().await?;
+            sqlx::query(
+                r#"
+                UPDATE batch_operation_items
+                SET status = $2, result = $3, error = $4, error_code = $5, processed_at = NOW()
+                WHERE id = $1
+                "#
+            )
+            .bind(item.id)
+            .bind(status)
+            .bind(result)
+            .bind(error)
+            .bind(error_code)
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query(
+                r#"
+                UPDATE batch_operations
+                SET processed_items = processed_items + 1,
+                    succeeded_items = succeeded_items + CASE WHEN $2 THEN 1 ELSE 0 END,
+                    failed_items = failed_items + CASE WHEN $2 THEN 0 ELSE 1 END,
+                    updated_at = NOW()
+                WHERE id = $1
+                "#
+            )
+            .bind(batch.id)
+            .bind(status == "succeeded")
+            .execute(&mut *tx)
+            .await?;
+            tx.commit().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Run one operation as the batch's user
+    async fn apply(&self, kind: BatchOperationKind, input: &serde_json::Value, user_id: Uuid) -> Result<serde_json::Value> {
+        match kind {
+            BatchOperationKind::CreateInquiries => {
+                let request = serde_json::from_value(input.clone())?;
+                let inquiry = place_inquiry(&self.db_pool, &self.encryption_key, request, user_id).await?;
+                Ok(serde_json::to_value(inquiry)?)
+            }
+            BatchOperationKind::UpdateInventory => {
+                let operation: InventoryUpdateOperation = serde_json::from_value(input.clone())?;
+
+                // Companies with expired licenses can't list until a renewal is verified
+                ListingRightsService::new(self.db_pool.clone()).ensure_can_list(user_id).await?;
+
+                let inventory = InventoryService::new(
+                    InventoryRepository::new(self.db_pool.clone()),
+                    PharmaceuticalRepository::new(self.db_pool.clone()),
+                )
+                .update_inventory(operation.id, user_id, operation.changes)
+                .await?;
+                Ok(serde_json::to_value(inventory)?)
+            }
+            BatchOperationKind::DismissNotifications => {
+                let operation: NotificationDismissOperation = serde_json::from_value(input.clone())?;
+                NotificationService::new(self.db_pool.clone())
+                    .dismiss_notification(operation.id, user_id)
+                    .await?;
+                Ok(json!({ "id": operation.id, "dismissed": true }))
+            }
+        }
+    }
+}
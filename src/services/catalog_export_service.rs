@@ -0,0 +1,137 @@
+/// Catalog Export Service
+///
+/// Streams the synced OpenFDA and EMA catalogs as CSV or NDJSON. Rows are read in
+/// pages ordered by the catalog key and encoded as they arrive, so exports of the
+/// full catalog never sit in memory. Downloads resume with `after=<key>` or by
+/// skipping the records already received (`Range: records=N-`).
+
+use std::future::Future;
+use std::sync::Arc;
+use futures::stream::{self, BoxStream, StreamExt};
+use sqlx::PgPool;
+use crate::{
+    middleware::error_handling::{AppError, Result},
+    models::catalog_export::*,
+    repositories::{EmaRepository, OpenFdaRepository},
+};
+
+/// A prepared export: the number of records it covers and the encoded byte stream
+pub struct CatalogExport {
+    pub format: ExportFormat,
+    /// Records matching the filters (before `offset` is applied)
+    pub total: i64,
+    /// Records skipped at the start of the export
+    pub offset: i64,
+    pub stream: BoxStream<'static, Result<Vec<u8>>>,
+}
+
+pub struct CatalogExportService {
+    db_pool: PgPool,
+}
+
+impl CatalogExportService {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self { db_pool }
+    }
+
+    pub async fn export_openfda(&self, params: OpenFdaExportParams, offset: i64) -> Result<CatalogExport> {
+        let format = ExportFormat::parse(params.format.as_deref())?;
+        let repo = Arc::new(OpenFdaRepository::new(self.db_pool.clone()));
+        let total = repo.count_export(&params).await?;
+        check_offset(offset, total)?;
+
+        tracing::info!("Exporting OpenFDA catalog as {:?}: {} records from offset {}", format, total, offset);
+
+        let params = Arc::new(params);
+        let after = params.after.clone();
+        let stream = export_stream(format, after, offset, move |after, offset| {
+            let repo = Arc::clone(&repo);
+            let params = Arc::clone(&params);
+            async move { repo.export_page(&params, after.as_deref(), offset, EXPORT_PAGE_SIZE).await }
+        });
+
+        Ok(CatalogExport { format, total, offset, stream })
+    }
+
+    pub async fn export_ema(&self, params: EmaExportParams, offset: i64) -> Result<CatalogExport> {
+        let format = ExportFormat::parse(params.format.as_deref())?;
+        let repo = Arc::new(EmaRepository::new(self.db_pool.clone()));
+        let total = repo.count_export(&params).await?;
+        check_offset(offset, total)?;
+
+        tracing::info!("Exporting EMA catalog as {:?}: {} records from offset {}", format, total, offset);
+
+        let params = Arc::new(params);
+        let after = params.after.clone();
+        let stream = export_stream(format, after, offset, move |after, offset| {
+            let repo = Arc::clone(&repo);
+            let params = Arc::clone(&params);
+            async move { repo.export_page(&params, after.as_deref(), offset, EXPORT_PAGE_SIZE).await }
+        });
+
+        Ok(CatalogExport { format, total, offset, stream })
+    }
+}
+
+fn check_offset(offset: i64, total: i64) -> Result<()> {
+    if offset > 0 && offset >= total {
+        return Err(AppError::BadRequest(format!(
+            "Range starts at record {} but the export has {} records",
+            offset, total
+        )));
+    }
+    Ok(())
+}
+
+/// Page through `fetch_page(after, offset)` and encode each page as one chunk. Only the
+/// first page skips `offset` rows; later pages continue after the last key sent.
+fn export_stream<T, F, Fut>(
+    format: ExportFormat,
+    after: Option<String>,
+    offset: i64,
+    fetch_page: F,
+) -> BoxStream<'static, Result<Vec<u8>>>
+where
+    T: ExportRecord + Send + 'static,
+    F: Fn(Option<String>, i64) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<Vec<T>>> + Send + 'static,
+{
+    struct Cursor {
+        after: Option<String>,
+        offset: i64,
+        with_header: bool,
+        done: bool,
+    }
+
+    // A resumed download continues a file that already has its header
+    let with_header = after.is_none() && offset == 0;
+    let fetch_page = Arc::new(fetch_page);
+
+    stream::try_unfold(
+        Cursor { after, offset, with_header, done: false },
+        move |mut cursor| {
+            let fetch_page = Arc::clone(&fetch_page);
+            async move {
+                if cursor.done {
+                    return Ok(None);
+                }
+
+                let page = fetch_page(cursor.after.clone(), cursor.offset).await?;
+                let chunk = encode_records(format, &page, cursor.with_header)?;
+
+                cursor.done = (page.len() as i64) < EXPORT_PAGE_SIZE;
+                cursor.with_header = false;
+                cursor.offset = 0;
+                if let Some(last) = page.last() {
+                    cursor.after = Some(last.export_key().to_string());
+                }
+
+                if chunk.is_empty() {
+                    return Ok(None);
+                }
+                Ok(Some((chunk, cursor)))
+            }
+        },
+    )
+    .boxed()
+}
@@ -0,0 +1,855 @@
+/// Catalog Change-Subscription Service
+///
+/// Detects status changes on subscribed OpenFDA/EMA records during sync and
+/// notifies subscribers in-app and/or via signed webhooks.
+///
+/// Flow:
+/// 1. Sync paths call `capture_*_changes()` with each batch BEFORE upserting it.
+///    Only records that somebody subscribes to are diffed, so unsubscribed
+///    records cost one indexed lookup per batch.
+/// 2. Detected changes are persisted to `catalog_change_events`.
+/// 3. `dispatch_pending_events()` runs after the sync, claims pending events and
+///    delivers each to every matching subscription, logging the outcome per
+///    channel. A failed delivery is logged and doesn't hold up the others.
+
+use std::collections::HashMap;
+use std::time::Duration;
+use chrono::{NaiveDate, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::{
+    middleware::error_handling::{AppError, Result},
+    models::{
+        alerts::AlertPayload,
+        catalog_subscription::*,
+        ema::EmaCatalogEntry,
+        openfda::OpenFdaCatalogEntry,
+    },
+    services::{
+        outbound_webhook_service::{is_internal_host, resolves_to_internal_address},
+        EncryptionService, NotificationService,
+    },
+};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Maximum subscriptions per user
+const MAX_SUBSCRIPTIONS_PER_USER: i64 = 500;
+
+/// Events dispatched per call to `dispatch_pending_events`
+const DISPATCH_BATCH_SIZE: i64 = 500;
+
+/// EMA statuses that mean the product is no longer marketed
+const EMA_DISCONTINUED_STATUSES: &[&str] = &["withdrawn", "revoked", "expired", "lapsed", "not renewed", "refused"];
+
+pub struct CatalogSubscriptionService {
+    db_pool: PgPool,
+}
+
+impl CatalogSubscriptionService {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self { db_pool }
+    }
+
+    // ========================================================================
+    // SUBSCRIPTION CRUD
+    // ========================================================================
+
+    pub async fn create_subscription(
+        &self,
+        user_id: Uuid,
+        request: CreateCatalogSubscriptionRequest,
+    ) -> Result<CreatedCatalogSubscriptionResponse> {
+        let identifier = request.identifier.trim().to_string();
+        validate_identifier(request.source, &identifier)?;
+
+        let notify_in_app = request.notify_in_app.unwrap_or(true);
+        let webhook_url = normalize_webhook_url(request.webhook_url)?;
+        if !notify_in_app && webhook_url.is_none() {
+            return Err(AppError::BadRequest(
+                "Enable in-app notifications or provide a webhook_url".to_string(),
+            ));
+        }
+
+        let change_types = change_types_or_default(request.change_types)?;
+
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM catalog_subscriptions WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_one(&self.db_pool)
+            .await?;
+
+        if count >= MAX_SUBSCRIPTIONS_PER_USER {
+            return Err(AppError::BadRequest(format!(
+                "Subscription limit reached ({} per account)",
+                MAX_SUBSCRIPTIONS_PER_USER
+            )));
+        }
+
+        let (webhook_secret, webhook_secret_encrypted) = if webhook_url.is_some() {
+            let secret = generate_webhook_secret();
+            let encrypted = encryption_service()?.encrypt(&secret)?;
+            (Some(secret), Some(encrypted))
+        } else {
+            (None, None)
+        };
+
+        let subscription = sqlx::query_as::<_, CatalogSubscription>(
+            r#"
+            INSERT INTO catalog_subscriptions (
+                user_id, source, identifier, change_types,
+                notify_in_app, webhook_url, webhook_secret_encrypted
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING *
+            "#
+        )
+        .bind(user_id)
+        .bind(request.source.as_str())
+        .bind(&identifier)
+        .bind(&change_types)
+        .bind(notify_in_app)
+        .bind(&webhook_url)
+        .bind(&webhook_secret_encrypted)
+        .fetch_one(&self.db_pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db) if db.code().as_deref() == Some("23505") => AppError::Conflict,
+            other => AppError::Database(other),
+        })?;
+
+        tracing::info!(
+            "Catalog subscription {} created by {} for {} {}",
+            subscription.id,
+            user_id,
+            subscription.source,
+            crate::utils::log_sanitizer::sanitize_for_log(&subscription.identifier)
+        );
+
+        Ok(CreatedCatalogSubscriptionResponse {
+            subscription,
+            webhook_secret,
+        })
+    }
+
+    pub async fn list_subscriptions(&self, user_id: Uuid) -> Result<Vec<CatalogSubscription>> {
+        let subscriptions = sqlx::query_as::<_, CatalogSubscription>(
+            "SELECT * FROM catalog_subscriptions WHERE user_id = $1 ORDER BY created_at DESC"
+        )
+        .bind(user_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(subscriptions)
+    }
+
+    pub async fn get_subscription(&self, subscription_id: Uuid, user_id: Uuid) -> Result<CatalogSubscription> {
+        sqlx::query_as::<_, CatalogSubscription>(
+            "SELECT * FROM catalog_subscriptions WHERE id = $1 AND user_id = $2"
+        )
+        .bind(subscription_id)
+        .bind(user_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Subscription not found".to_string()))
+    }
+
+    pub async fn update_subscription(
+        &self,
+        subscription_id: Uuid,
+        user_id: Uuid,
+        request: UpdateCatalogSubscriptionRequest,
+    ) -> Result<CatalogSubscription> {
+        let existing = self.get_subscription(subscription_id, user_id).await?;
+
+        let change_types = match request.change_types {
+            Some(types) => change_types_or_default(Some(types))?,
+            None => existing.change_types.clone(),
+        };
+
+        let webhook_url = match request.webhook_url {
+            Some(url) => normalize_webhook_url(Some(url))?,
+            None => existing.webhook_url.clone(),
+        };
+
+        let notify_in_app = request.notify_in_app.unwrap_or(existing.notify_in_app);
+        if !notify_in_app && webhook_url.is_none() {
+            return Err(AppError::BadRequest(
+                "Enable in-app notifications or provide a webhook_url".to_string(),
+            ));
+        }
+
+        // A webhook added after creation needs a signing secret
+        let webhook_secret_encrypted = match (&webhook_url, &existing.webhook_secret_encrypted) {
+            (None, _) => None,
+            (Some(_), Some(secret)) => Some(secret.clone()),
+            (Some(_), None) => Some(encryption_service()?.encrypt(&generate_webhook_secret())?),
+        };
+
+        let subscription = sqlx::query_as::<_, CatalogSubscription>(
+            r#"
+            UPDATE catalog_subscriptions
+            SET change_types = $3,
+                notify_in_app = $4,
+                webhook_url = $5,
+                webhook_secret_encrypted = $6,
+                is_active = $7,
+                updated_at = NOW()
+            WHERE id = $1 AND user_id = $2
+            RETURNING *
+            "#
+        )
+        .bind(subscription_id)
+        .bind(user_id)
+        .bind(&change_types)
+        .bind(notify_in_app)
+        .bind(&webhook_url)
+        .bind(&webhook_secret_encrypted)
+        .bind(request.is_active.unwrap_or(existing.is_active))
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        Ok(subscription)
+    }
+
+    pub async fn delete_subscription(&self, subscription_id: Uuid, user_id: Uuid) -> Result<()> {
+        let result = sqlx::query("DELETE FROM catalog_subscriptions WHERE id = $1 AND user_id = $2")
+            .bind(subscription_id)
+            .bind(user_id)
+            .execute(&self.db_pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Subscription not found".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Change history for the record a subscription watches
+    pub async fn get_subscription_events(
+        &self,
+        subscription_id: Uuid,
+        user_id: Uuid,
+        query: CatalogChangeEventsQuery,
+    ) -> Result<Vec<CatalogChangeEvent>> {
+        let subscription = self.get_subscription(subscription_id, user_id).await?;
+
+        let events = sqlx::query_as::<_, CatalogChangeEvent>(
+            r#"
+            SELECT * FROM catalog_change_events
+            WHERE source = $1 AND identifier = $2
+            ORDER BY detected_at DESC
+            LIMIT $3 OFFSET $4
+            "#
+        )
+        .bind(&subscription.source)
+        .bind(&subscription.identifier)
+        .bind(query.limit.unwrap_or(50).clamp(1, 100))
+        .bind(query.offset.unwrap_or(0).max(0))
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(events)
+    }
+
+    // ========================================================================
+    // CHANGE CAPTURE (called from sync upsert paths)
+    // ========================================================================
+
+    /// Diff an OpenFDA batch against the stored records. Call before `batch_upsert`.
+    pub async fn capture_openfda_changes(
+        &self,
+        entries: &[OpenFdaCatalogEntry],
+        sync_log_id: Option<Uuid>,
+    ) -> Result<usize> {
+        let incoming: Vec<CatalogSnapshot> = entries
+            .iter()
+            .map(|e| CatalogSnapshot {
+                identifier: e.product_ndc.clone(),
+                product_name: e.brand_name.clone(),
+                status: e.marketing_category.clone(),
+                expiration_date: e.listing_expiration_date,
+            })
+            .collect();
+
+        self.capture_changes(CatalogSource::Openfda, incoming, sync_log_id).await
+    }
+
+    /// Diff an EMA batch against the stored records. Call before `batch_upsert`.
+    pub async fn capture_ema_changes(
+        &self,
+        entries: &[EmaCatalogEntry],
+        sync_log_id: Option<Uuid>,
+    ) -> Result<usize> {
+        let incoming: Vec<CatalogSnapshot> = entries
+            .iter()
+            .map(|e| CatalogSnapshot {
+                identifier: e.eu_number.clone(),
+                product_name: e.product_name.clone(),
+                status: e.authorization_status.clone(),
+                expiration_date: None,
+            })
+            .collect();
+
+        self.capture_changes(CatalogSource::Ema, incoming, sync_log_id).await
+    }
+
+    async fn capture_changes(
+        &self,
+        source: CatalogSource,
+        incoming: Vec<CatalogSnapshot>,
+        sync_log_id: Option<Uuid>,
+    ) -> Result<usize> {
+        if incoming.is_empty() {
+            return Ok(0);
+        }
+
+        let identifiers: Vec<String> = incoming.iter().map(|s| s.identifier.clone()).collect();
+
+        let existing_query = match source {
+            CatalogSource::Openfda => r#"
+                SELECT c.product_ndc AS identifier, c.brand_name AS product_name,
+                       c.marketing_category AS status, c.listing_expiration_date AS expiration_date
+                FROM openfda_catalog c
+                WHERE c.product_ndc = ANY($1)
+                  AND EXISTS (
+                      SELECT 1 FROM catalog_subscriptions s
+                      WHERE s.source = 'openfda' AND s.identifier = c.product_ndc AND s.is_active = TRUE
+                  )
+            "#,
+            CatalogSource::Ema => r#"
+                SELECT c.eu_number AS identifier, c.product_name,
+                       c.authorization_status AS status, NULL::DATE AS expiration_date
+                FROM ema_catalog c
+                WHERE c.eu_number = ANY($1)
+                  AND EXISTS (
+                      SELECT 1 FROM catalog_subscriptions s
+                      WHERE s.source = 'ema' AND s.identifier = c.eu_number AND s.is_active = TRUE
+                  )
+            "#,
+        };
+
+        let existing: HashMap<String, CatalogSnapshot> = sqlx::query_as::<_, CatalogSnapshot>(existing_query)
+            .bind(&identifiers)
+            .fetch_all(&self.db_pool)
+            .await?
+            .into_iter()
+            .map(|s| (s.identifier.clone(), s))
+            .collect();
+
+        if existing.is_empty() {
+            return Ok(0);
+        }
+
+        let today = Utc::now().date_naive();
+        let changes: Vec<DetectedCatalogChange> = incoming
+            .iter()
+            .filter_map(|new| existing.get(&new.identifier).map(|old| detect_changes(source, old, new, today)))
+            .flatten()
+            .collect();
+
+        self.record_changes(&changes, sync_log_id).await
+    }
+
+    /// Persist detected changes so they can be dispatched after the sync.
+    /// Also used by sync paths that produce changes directly (e.g. recall feeds).
+    pub async fn record_changes(
+        &self,
+        changes: &[DetectedCatalogChange],
+        sync_log_id: Option<Uuid>,
+    ) -> Result<usize> {
+        for change in changes {
+            sqlx::query(
+                r#"
+                INSERT INTO catalog_change_events (
+                    source, identifier, product_name, change_type, old_value, new_value, sync_log_id
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                "#
+            )
+            .bind(change.source.as_str())
+            .bind(&change.identifier)
+            .bind(&change.product_name)
+            .bind(change.change_type.as_str())
+            .bind(&change.old_value)
+            .bind(&change.new_value)
+            .bind(sync_log_id)
+            .execute(&self.db_pool)
+            .await?;
+        }
+
+        if !changes.is_empty() {
+            tracing::info!("📬 Recorded {} catalog change event(s) for subscribers", changes.len());
+        }
+
+        Ok(changes.len())
+    }
+
+    // ========================================================================
+    // DISPATCH
+    // ========================================================================
+
+    /// Deliver all undispatched change events to their subscribers.
+    /// Returns the number of deliveries attempted.
+    ///
+    /// Events are claimed (marked dispatched) before delivery, so concurrent
+    /// dispatchers never pick up the same event. A failed delivery is logged
+    /// and the others go ahead; it isn't retried.
+    pub async fn dispatch_pending_events(&self) -> Result<usize> {
+        let events = sqlx::query_as::<_, CatalogChangeEvent>(
+            r#"
+            UPDATE catalog_change_events
+            SET dispatched_at = NOW()
+            WHERE id IN (
+                SELECT id FROM catalog_change_events
+                WHERE dispatched_at IS NULL
+                ORDER BY detected_at ASC
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING *
+            "#
+        )
+        .bind(DISPATCH_BATCH_SIZE)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        if events.is_empty() {
+            return Ok(0);
+        }
+
+        let notification_service = NotificationService::new(self.db_pool.clone());
+        // 🔒 SECURITY: A redirect could lead the request into our own network
+        let http_client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .unwrap_or_default();
+        let mut encryption: Option<EncryptionService> = None;
+        let mut deliveries = 0;
+
+        for event in events {
+            let subscriptions = match self.subscriptions_for(&event).await {
+                Ok(subscriptions) => subscriptions,
+                Err(e) => {
+                    // Nothing was delivered yet; hand the event back to the next run
+                    tracing::error!("Failed to load subscriptions for catalog change event {}: {}", event.id, e);
+                    self.release_event(event.id).await;
+                    continue;
+                }
+            };
+
+            for subscription in &subscriptions {
+                if subscription.notify_in_app {
+                    let payload = AlertPayload::new_catalog_change(
+                        subscription.user_id,
+                        &event.source,
+                        &event.identifier,
+                        event.product_name.as_deref().unwrap_or(&event.identifier),
+                        &event.change_type,
+                        event.old_value.as_deref(),
+                        event.new_value.as_deref(),
+                    );
+
+                    let outcome = notification_service.create_alert(payload).await.map(|_| None);
+                    self.log_delivery(subscription.id, event.id, "in_app", outcome).await;
+                    deliveries += 1;
+                }
+
+                if let (Some(url), Some(secret_encrypted)) =
+                    (&subscription.webhook_url, &subscription.webhook_secret_encrypted)
+                {
+                    let outcome = match webhook_secret(&mut encryption, secret_encrypted) {
+                        Ok(secret) => send_webhook(&http_client, url, &secret, subscription.id, &event).await,
+                        Err(e) => Err(e),
+                    };
+                    self.log_delivery(subscription.id, event.id, "webhook", outcome).await;
+                    deliveries += 1;
+                }
+            }
+
+            if !subscriptions.is_empty() {
+                let ids: Vec<Uuid> = subscriptions.iter().map(|s| s.id).collect();
+                if let Err(e) = sqlx::query("UPDATE catalog_subscriptions SET last_notified_at = NOW() WHERE id = ANY($1)")
+                    .bind(&ids)
+                    .execute(&self.db_pool)
+                    .await
+                {
+                    tracing::error!("Failed to update last_notified_at for catalog change event {}: {}", event.id, e);
+                }
+            }
+        }
+
+        tracing::info!("📬 Catalog subscription dispatch complete: {} deliveries", deliveries);
+
+        Ok(deliveries)
+    }
+
+    async fn subscriptions_for(&self, event: &CatalogChangeEvent) -> Result<Vec<CatalogSubscription>> {
+        let subscriptions = sqlx::query_as::<_, CatalogSubscription>(
+            r#"
+            SELECT * FROM catalog_subscriptions
+            WHERE source = $1 AND identifier = $2 AND is_active = TRUE
+              AND $3 = ANY(change_types)
+            "#
+        )
+        .bind(&event.source)
+        .bind(&event.identifier)
+        .bind(&event.change_type)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(subscriptions)
+    }
+
+    /// Undo the claim on an event that wasn't delivered to anyone
+    async fn release_event(&self, event_id: Uuid) {
+        if let Err(e) = sqlx::query("UPDATE catalog_change_events SET dispatched_at = NULL WHERE id = $1")
+            .bind(event_id)
+            .execute(&self.db_pool)
+            .await
+        {
+            tracing::error!("Failed to release catalog change event {}: {}", event_id, e);
+        }
+    }
+
+    /// Record the outcome of a delivery; a failure to record it is only logged
+    async fn log_delivery(
+        &self,
+        subscription_id: Uuid,
+        event_id: Uuid,
+        channel: &str,
+        outcome: Result<Option<u16>>,
+    ) {
+        let (status, http_status, error_message) = match outcome {
+            Ok(code) => ("delivered", code.map(i32::from), None),
+            Err(e) => {
+                tracing::warn!(
+                    "Catalog subscription {} {} delivery failed: {}",
+                    subscription_id, channel, e
+                );
+                ("failed", None, Some(e.to_string()))
+            }
+        };
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO catalog_subscription_deliveries (
+                subscription_id, event_id, channel, status, http_status, error_message
+            )
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#
+        )
+        .bind(subscription_id)
+        .bind(event_id)
+        .bind(channel)
+        .bind(status)
+        .bind(http_status)
+        .bind(error_message)
+        .execute(&self.db_pool)
+        .await;
+
+        if let Err(e) = result {
+            tracing::error!(
+                "Failed to log {} delivery of event {} to catalog subscription {}: {}",
+                channel, event_id, subscription_id, e
+            );
+        }
+    }
+}
+
+/// Compare a stored record with its incoming version and classify what changed
+pub fn detect_changes(
+    source: CatalogSource,
+    old: &CatalogSnapshot,
+    new: &CatalogSnapshot,
+    today: NaiveDate,
+) -> Vec<DetectedCatalogChange> {
+    let mut changes = Vec::new();
+
+    let make = |change_type: CatalogChangeType, old_value: Option<String>, new_value: Option<String>| {
+        DetectedCatalogChange {
+            source,
+            identifier: new.identifier.clone(),
+            product_name: new.product_name.clone(),
+            change_type,
+            old_value,
+            new_value,
+        }
+    };
+
+    let normalize = |s: &Option<String>| s.as_deref().map(|v| v.trim().to_lowercase()).filter(|v| !v.is_empty());
+    let old_status = normalize(&old.status);
+    let new_status = normalize(&new.status);
+
+    if old_status != new_status {
+        let change_type = match (source, new_status.as_deref()) {
+            (CatalogSource::Ema, Some(status)) if status.contains("suspend") => CatalogChangeType::Recall,
+            (CatalogSource::Ema, Some(status))
+                if EMA_DISCONTINUED_STATUSES.iter().any(|s| status.contains(s)) =>
+            {
+                CatalogChangeType::Discontinuation
+            }
+            _ => CatalogChangeType::StatusChange,
+        };
+        changes.push(make(change_type, old.status.clone(), new.status.clone()));
+    }
+
+    // OpenFDA: a listing whose expiration date has moved into the past is no longer marketed
+    let was_listed = old.expiration_date.map(|d| d >= today).unwrap_or(true);
+    let is_expired = new.expiration_date.map(|d| d < today).unwrap_or(false);
+    if was_listed && is_expired {
+        changes.push(make(
+            CatalogChangeType::Discontinuation,
+            old.expiration_date.map(|d| d.to_string()),
+            new.expiration_date.map(|d| d.to_string()),
+        ));
+    }
+
+    changes
+}
+
+fn validate_identifier(source: CatalogSource, identifier: &str) -> Result<()> {
+    let valid = match source {
+        // Product NDC: labeler-product, e.g. 0002-3227 or 50090-1234
+        CatalogSource::Openfda => {
+            identifier.len() <= 20
+                && identifier.contains('-')
+                && identifier.chars().all(|c| c.is_ascii_digit() || c == '-')
+        }
+        // EU number: EU/1/00/000/001
+        CatalogSource::Ema => identifier.len() <= 50 && identifier.to_uppercase().starts_with("EU/"),
+    };
+
+    if valid {
+        Ok(())
+    } else {
+        Err(AppError::InvalidInput(format!("Invalid {} identifier", source)))
+    }
+}
+
+fn change_types_or_default(types: Option<Vec<CatalogChangeType>>) -> Result<Vec<String>> {
+    let types = types.unwrap_or_else(|| {
+        vec![
+            CatalogChangeType::StatusChange,
+            CatalogChangeType::Discontinuation,
+            CatalogChangeType::Recall,
+        ]
+    });
+
+    if types.is_empty() {
+        return Err(AppError::BadRequest("At least one change type is required".to_string()));
+    }
+
+    let mut names: Vec<String> = types.iter().map(|t| t.as_str().to_string()).collect();
+    names.sort();
+    names.dedup();
+    Ok(names)
+}
+
+fn normalize_webhook_url(url: Option<String>) -> Result<Option<String>> {
+    let url = match url.map(|u| u.trim().to_string()).filter(|u| !u.is_empty()) {
+        Some(url) => url,
+        None => return Ok(None),
+    };
+
+    let parsed = url::Url::parse(&url)
+        .map_err(|_| AppError::InvalidInput("Invalid webhook_url".to_string()))?;
+
+    // 🔒 SECURITY: Only HTTPS endpoints receive signed catalog payloads
+    if parsed.scheme() != "https" || parsed.host_str().is_none() {
+        return Err(AppError::InvalidInput("webhook_url must be an https:// URL".to_string()));
+    }
+    // 🔒 SECURITY: No deliveries into our own network
+    if is_internal_host(&parsed) {
+        return Err(AppError::InvalidInput("webhook_url must be publicly reachable".to_string()));
+    }
+
+    Ok(Some(url))
+}
+
+fn generate_webhook_secret() -> String {
+    use rand::RngCore;
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+fn encryption_service() -> Result<EncryptionService> {
+    let key = std::env::var("ENCRYPTION_KEY")
+        .map_err(|_| AppError::Internal(anyhow::anyhow!("ENCRYPTION_KEY not set")))?;
+    Ok(EncryptionService::new(&key)?)
+}
+
+/// Decrypt a subscription's webhook secret, creating the encryption service on first use
+fn webhook_secret(encryption: &mut Option<EncryptionService>, secret_encrypted: &str) -> Result<String> {
+    if encryption.is_none() {
+        *encryption = Some(encryption_service()?);
+    }
+    encryption
+        .as_ref()
+        .ok_or_else(|| AppError::Internal(anyhow::anyhow!("Encryption service unavailable")))?
+        .decrypt(secret_encrypted)
+}
+
+/// HMAC-SHA256 signature in the same `sha256=<hex>` format we verify on inbound ERP webhooks
+pub fn sign_payload(secret: &str, payload: &[u8]) -> Result<String> {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes())
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("HMAC init failed: {:?}", e)))?;
+    mac.update(payload);
+    Ok(format!("sha256={}", hex::encode(mac.finalize().into_bytes())))
+}
+
+async fn send_webhook(
+    client: &reqwest::Client,
+    url: &str,
+    secret: &str,
+    subscription_id: Uuid,
+    event: &CatalogChangeEvent,
+) -> Result<Option<u16>> {
+    let payload = CatalogChangeWebhookPayload {
+        event_id: event.id,
+        subscription_id,
+        source: event.source.clone(),
+        identifier: event.identifier.clone(),
+        product_name: event.product_name.clone(),
+        change_type: event.change_type.clone(),
+        old_value: event.old_value.clone(),
+        new_value: event.new_value.clone(),
+        detected_at: event.detected_at,
+    };
+    let body = serde_json::to_vec(&payload)?;
+    let signature = sign_payload(secret, &body)?;
+
+    // 🔒 SECURITY: Checked again at send time: the URL may predate the registration
+    // checks, and its host may since resolve elsewhere
+    let target = normalize_webhook_url(Some(url.to_string()))?
+        .ok_or_else(|| AppError::InvalidInput("Invalid webhook_url".to_string()))?;
+    let parsed = url::Url::parse(&target)
+        .map_err(|_| AppError::InvalidInput("Invalid webhook_url".to_string()))?;
+    if resolves_to_internal_address(&parsed).await {
+        return Err(AppError::InvalidInput("webhook_url must be publicly reachable".to_string()));
+    }
+
+    let response = client
+        .post(parsed)
+        .header("Content-Type", "application/json")
+        .header("X-Atlas-Event", "catalog.change")
+        .header("X-Atlas-Event-Id", event.id.to_string())
+        .header("X-Webhook-Signature", signature)
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Webhook request failed: {}", e)))?;
+
+    let status = response.status().as_u16();
+    if response.status().is_success() {
+        Ok(Some(status))
+    } else {
+        Err(AppError::Internal(anyhow::anyhow!("Webhook endpoint returned HTTP {}", status)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(status: Option<&str>, expiration: Option<NaiveDate>) -> CatalogSnapshot {
+        CatalogSnapshot {
+            identifier: "0002-3227".to_string(),
+            product_name: "Test Product".to_string(),
+            status: status.map(str::to_string),
+            expiration_date: expiration,
+        }
+    }
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 6, 1).unwrap()
+    }
+
+    #[test]
+    fn test_no_change_detected_for_identical_records() {
+        let old = snapshot(Some("NDA"), None);
+        let new = snapshot(Some(" nda "), None);
+        assert!(detect_changes(CatalogSource::Openfda, &old, &new, today()).is_empty());
+    }
+
+    #[test]
+    fn test_openfda_marketing_category_change() {
+        let old = snapshot(Some("NDA"), None);
+        let new = snapshot(Some("NDA AUTHORIZED GENERIC"), None);
+        let changes = detect_changes(CatalogSource::Openfda, &old, &new, today());
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].change_type, CatalogChangeType::StatusChange);
+    }
+
+    #[test]
+    fn test_openfda_listing_expiry_is_discontinuation() {
+        let old = snapshot(Some("NDA"), NaiveDate::from_ymd_opt(2025, 12, 31));
+        let new = snapshot(Some("NDA"), NaiveDate::from_ymd_opt(2025, 5, 1));
+        let changes = detect_changes(CatalogSource::Openfda, &old, &new, today());
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].change_type, CatalogChangeType::Discontinuation);
+
+        // Already expired before this sync - no duplicate notification
+        let again = detect_changes(CatalogSource::Openfda, &new, &new, today());
+        assert!(again.is_empty());
+    }
+
+    #[test]
+    fn test_ema_status_classification() {
+        let authorised = snapshot(Some("Authorised"), None);
+
+        let suspended = detect_changes(CatalogSource::Ema, &authorised, &snapshot(Some("Suspended"), None), today());
+        assert_eq!(suspended[0].change_type, CatalogChangeType::Recall);
+
+        let withdrawn = detect_changes(CatalogSource::Ema, &authorised, &snapshot(Some("Withdrawn"), None), today());
+        assert_eq!(withdrawn[0].change_type, CatalogChangeType::Discontinuation);
+
+        let conditional = detect_changes(CatalogSource::Ema, &authorised, &snapshot(Some("Conditional"), None), today());
+        assert_eq!(conditional[0].change_type, CatalogChangeType::StatusChange);
+    }
+
+    #[test]
+    fn test_identifier_validation() {
+        assert!(validate_identifier(CatalogSource::Openfda, "0002-3227").is_ok());
+        assert!(validate_identifier(CatalogSource::Openfda, "abc").is_err());
+        assert!(validate_identifier(CatalogSource::Ema, "EU/1/00/000/001").is_ok());
+        assert!(validate_identifier(CatalogSource::Ema, "0002-3227").is_err());
+    }
+
+    #[test]
+    fn test_webhook_url_requires_https() {
+        assert!(normalize_webhook_url(Some("http://example.com/hook".to_string())).is_err());
+        assert_eq!(
+            normalize_webhook_url(Some(" https://example.com/hook ".to_string())).unwrap(),
+            Some("https://example.com/hook".to_string())
+        );
+        assert_eq!(normalize_webhook_url(Some("".to_string())).unwrap(), None);
+    }
+
+    #[test]
+    fn test_webhook_url_rejects_internal_hosts() {
+        for url in [
+            "https://localhost/hook",
+            "https://127.0.0.1/hook",
+            "https://10.1.2.3/hook",
+            "https://192.168.0.10/hook",
+            "https://169.254.169.254/latest/meta-data",
+            "https://[::1]/hook",
+            "https://[fe80::1]/hook",
+            "https://metadata.internal/hook",
+        ] {
+            assert!(normalize_webhook_url(Some(url.to_string())).is_err(), "{}", url);
+        }
+    }
+
+    #[test]
+    fn test_payload_signature_format() {
+        let signature = sign_payload("secret", b"{}").unwrap();
+        assert!(signature.starts_with("sha256="));
+        assert_eq!(signature.len(), "sha256=".len() + 64);
+    }
+}
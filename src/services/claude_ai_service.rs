@@ -3,6 +3,9 @@
 
 use serde::{Deserialize, Serialize};
 use crate::middleware::error_handling::{Result, AppError};
+use crate::services::ai_response_cache_service::{AiFeature, AiCacheKey, AiResponseCacheService};
+use crate::services::ai_output_validation::{parse_validated, repair_prompt, AiOutputError};
+use serde::de::DeserializeOwned;
 use std::time::Instant;
 use sqlx::PgPool;
 use uuid::Uuid;
@@ -10,7 +13,7 @@ use uuid::Uuid;
 // Default to official Anthropic API, but can be overridden with env var for proxies like z.ai
 const DEFAULT_CLAUDE_API_URL: &str = "https://api.anthropic.com/v1/messages";
 const CLAUDE_MODEL: &str = "claude-3-5-sonnet-20241022";
-const CLAUDE_VERSION: &str = "2023-06-01";
+pub(crate) const CLAUDE_VERSION: &str = "2023-06-01";
 
 // Pricing per million tokens (as of 2025)
 const INPUT_COST_PER_MILLION: f64 = 3.0;
@@ -32,7 +35,44 @@ struct ClaudeRequest {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ClaudeMessage {
     pub role: String, // "user" or "assistant"
-    pub content: String,
+    pub content: ClaudeContent,
+}
+
+/// Message content: plain text, or blocks mixing text with documents and images
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum ClaudeContent {
+    Text(String),
+    Blocks(Vec<ClaudeContentBlock>),
+}
+
+impl From<String> for ClaudeContent {
+    fn from(text: String) -> Self {
+        ClaudeContent::Text(text)
+    }
+}
+
+impl From<&str> for ClaudeContent {
+    fn from(text: &str) -> Self {
+        ClaudeContent::Text(text.to_string())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClaudeContentBlock {
+    Text { text: String },
+    /// PDF pages are read by the model (including scanned pages)
+    Document { source: ClaudeMediaSource },
+    Image { source: ClaudeMediaSource },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ClaudeMediaSource {
+    #[serde(rename = "type")]
+    pub source_type: String, // "base64"
+    pub media_type: String,
+    pub data: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -72,9 +112,22 @@ pub struct ClaudeApiResponse {
     pub output_tokens: u32,
     pub cost_usd: f64,
     pub latency_ms: u64,
+    /// Served from the response cache: no tokens were billed and no quota was used
+    pub cached: bool,
+    /// Where to store the response once the caller has validated it
+    pub cache_key: Option<AiCacheKey>,
+}
+
+/// A response that passed its feature's output schema
+#[derive(Debug)]
+pub struct ValidatedAiResponse<T> {
+    pub value: T,
+    /// Content is the validated answer; tokens and cost include the repair call, if any
+    pub response: ClaudeApiResponse,
 }
 
 /// Configuration for Claude AI requests
+#[derive(Clone)]
 pub struct ClaudeRequestConfig {
     pub max_tokens: u32,
     pub temperature: Option<f32>,
@@ -95,10 +148,16 @@ impl Default for ClaudeRequestConfig {
 // Claude AI Service
 // ============================================================================
 
+/// Messages endpoint: ANTHROPIC_BASE_URL (e.g. a proxy) or the Anthropic API
+pub(crate) fn claude_api_url() -> String {
+    std::env::var("ANTHROPIC_BASE_URL").unwrap_or_else(|_| DEFAULT_CLAUDE_API_URL.to_string())
+}
+
 pub struct ClaudeAIService {
     api_key: String,
     http_client: reqwest::Client,
     db_pool: PgPool,
+    cache: AiResponseCacheService,
 }
 
 impl ClaudeAIService {
@@ -106,6 +165,7 @@ impl ClaudeAIService {
         Self {
             api_key,
             http_client: reqwest::Client::new(),
+            cache: AiResponseCacheService::new(db_pool.clone()),
             db_pool,
         }
     }
@@ -118,27 +178,166 @@ impl ClaudeAIService {
         user_id: Uuid,
         session_id: Option<Uuid>,
     ) -> Result<ClaudeApiResponse> {
-        // CRITICAL: Check quota BEFORE making API call (prevents cost attacks)
-        if !self.check_and_reserve_quota(user_id).await? {
-            return Err(AppError::QuotaExceeded(
-                "Monthly AI usage limit exceeded. Please upgrade your plan or wait for monthly reset.".to_string()
-            ));
+        self.send_request(Self::build_request(messages, config), user_id, session_id).await
+    }
+
+    /// Like `send_message`, but an identical earlier request of the same feature and user
+    /// is answered from the response cache without reserving quota or calling the API.
+    /// On a miss, call `cache_response` once the response has been validated.
+    pub async fn send_message_cached(
+        &self,
+        messages: Vec<ClaudeMessage>,
+        config: ClaudeRequestConfig,
+        user_id: Uuid,
+        session_id: Option<Uuid>,
+        feature: AiFeature,
+    ) -> Result<ClaudeApiResponse> {
+        let request = Self::build_request(messages, config);
+        if !self.cache.config().is_enabled(feature) {
+            return self.send_request(request, user_id, session_id).await;
         }
 
         let start_time = Instant::now();
+        let key = AiCacheKey::new(feature, user_id, &serde_json::to_vec(&request)?);
+
+        // A cache failure must never fail the AI request itself
+        match self.cache.get(&key).await {
+            Ok(Some(cached)) => {
+                tracing::info!(
+                    "Claude API cache hit: user={}, feature={}, saved=${}",
+                    user_id,
+                    feature.as_str(),
+                    cached.cost_usd
+                );
+
+                return Ok(ClaudeApiResponse {
+                    content: cached.content,
+                    input_tokens: 0,
+                    output_tokens: 0,
+                    cost_usd: 0.0,
+                    latency_ms: start_time.elapsed().as_millis() as u64,
+                    cached: true,
+                    cache_key: Some(key),
+                });
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!("AI response cache lookup failed: {:?}", e),
+        }
+
+        let mut response = self.send_request(request, user_id, session_id).await?;
+        response.cache_key = Some(key);
+        Ok(response)
+    }
+
+    /// `send_message_cached` for features with structured output: the response is
+    /// extracted from any markdown fences, checked against the feature's schema and
+    /// deserialized. An invalid response gets one repair retry with the validation errors
+    /// fed back; if that also fails the error carries them as `AppError::AiOutputInvalid`.
+    /// Call `cache_response` with the returned response once it has been used.
+    pub async fn send_message_validated<T: DeserializeOwned>(
+        &self,
+        messages: Vec<ClaudeMessage>,
+        config: ClaudeRequestConfig,
+        user_id: Uuid,
+        session_id: Option<Uuid>,
+        feature: AiFeature,
+    ) -> Result<ValidatedAiResponse<T>> {
+        let response = self.send_message_cached(
+            messages.clone(),
+            config.clone(),
+            user_id,
+            session_id,
+            feature,
+        ).await?;
 
-        // Build request
-        let request = ClaudeRequest {
+        let errors = match parse_validated::<T>(feature, &response.content) {
+            Ok(value) => {
+                crate::middleware::metrics::record_ai_output_validation(feature.as_str(), "valid");
+                return Ok(ValidatedAiResponse { value, response });
+            }
+            Err(errors) => errors,
+        };
+
+        tracing::warn!(
+            "AI output failed validation ({}), retrying with repair prompt: {}",
+            feature.as_str(),
+            errors.join("; ")
+        );
+
+        let mut repair_messages = messages;
+        repair_messages.push(assistant_message(response.content.clone()));
+        repair_messages.push(user_message(repair_prompt(&errors)));
+        let repaired = self.send_message(repair_messages, config, user_id, session_id).await?;
+
+        match parse_validated::<T>(feature, &repaired.content) {
+            Ok(value) => {
+                crate::middleware::metrics::record_ai_output_validation(feature.as_str(), "repaired");
+                Ok(ValidatedAiResponse {
+                    value,
+                    response: ClaudeApiResponse {
+                        content: repaired.content,
+                        input_tokens: response.input_tokens + repaired.input_tokens,
+                        output_tokens: response.output_tokens + repaired.output_tokens,
+                        cost_usd: response.cost_usd + repaired.cost_usd,
+                        latency_ms: response.latency_ms + repaired.latency_ms,
+                        cached: false,
+                        // The repaired answer is what the original request should have returned
+                        cache_key: response.cache_key,
+                    },
+                })
+            }
+            Err(errors) => {
+                crate::middleware::metrics::record_ai_output_validation(feature.as_str(), "invalid");
+                tracing::debug!("Unrepairable AI output ({}): {}", feature.as_str(), repaired.content);
+                Err(AppError::AiOutputInvalid(AiOutputError { feature, errors }))
+            }
+        }
+    }
+
+    /// Store a validated response from `send_message_cached` (no-op for hits and uncached calls)
+    pub async fn cache_response(&self, response: &ClaudeApiResponse) {
+        let Some(key) = response.cache_key.as_ref().filter(|_| !response.cached) else {
+            return;
+        };
+
+        if let Err(e) = self.cache.put(
+            key,
+            &response.content,
+            response.input_tokens,
+            response.output_tokens,
+            response.cost_usd,
+        ).await {
+            tracing::warn!("Failed to cache AI response ({}): {:?}", key.feature.as_str(), e);
+        }
+    }
+
+    fn build_request(messages: Vec<ClaudeMessage>, config: ClaudeRequestConfig) -> ClaudeRequest {
+        ClaudeRequest {
             model: CLAUDE_MODEL.to_string(),
             max_tokens: config.max_tokens,
             messages,
             system: config.system_prompt,
             temperature: config.temperature,
-        };
+        }
+    }
+
+    async fn send_request(
+        &self,
+        request: ClaudeRequest,
+        user_id: Uuid,
+        session_id: Option<Uuid>,
+    ) -> Result<ClaudeApiResponse> {
+        // CRITICAL: Check quota BEFORE making API call (prevents cost attacks)
+        if !self.check_and_reserve_quota(user_id).await? {
+            return Err(AppError::QuotaExceeded(
+                "Monthly AI usage limit exceeded. Please upgrade your plan or wait for monthly reset.".to_string()
+            ));
+        }
+
+        let start_time = Instant::now();
 
         // Get API URL from env or use default
-        let api_url = std::env::var("ANTHROPIC_BASE_URL")
-            .unwrap_or_else(|_| DEFAULT_CLAUDE_API_URL.to_string());
+        let api_url = claude_api_url();
 
         // Send to Claude API (or proxy like z.ai)
         let response = self.http_client
@@ -206,6 +405,8 @@ impl ClaudeAIService {
             output_tokens: claude_response.usage.output_tokens,
             cost_usd: total_cost,
             latency_ms,
+            cached: false,
+            cache_key: None,
         })
     }
 
@@ -359,6 +560,10 @@ impl ClaudeAIService {
         .execute(&self.db_pool)
         .await?;
 
+        crate::services::UsageMeteringService::new(self.db_pool.clone())
+            .record(user_id, crate::models::billing::BillingMeter::AiCalls, 1)
+            .await;
+
         Ok(())
     }
 }
@@ -371,7 +576,31 @@ impl ClaudeAIService {
 pub fn user_message(content: impl Into<String>) -> ClaudeMessage {
     ClaudeMessage {
         role: "user".to_string(),
-        content: content.into(),
+        content: ClaudeContent::Text(content.into()),
+    }
+}
+
+/// Create a user message carrying a PDF or image followed by instructions
+pub fn user_message_with_attachment(media_type: &str, data: &[u8], instructions: impl Into<String>) -> ClaudeMessage {
+    use base64::Engine;
+
+    let source = ClaudeMediaSource {
+        source_type: "base64".to_string(),
+        media_type: media_type.to_string(),
+        data: base64::engine::general_purpose::STANDARD.encode(data),
+    };
+    let attachment = if media_type == "application/pdf" {
+        ClaudeContentBlock::Document { source }
+    } else {
+        ClaudeContentBlock::Image { source }
+    };
+
+    ClaudeMessage {
+        role: "user".to_string(),
+        content: ClaudeContent::Blocks(vec![
+            attachment,
+            ClaudeContentBlock::Text { text: instructions.into() },
+        ]),
     }
 }
 
@@ -379,6 +608,6 @@ pub fn user_message(content: impl Into<String>) -> ClaudeMessage {
 pub fn assistant_message(content: impl Into<String>) -> ClaudeMessage {
     ClaudeMessage {
         role: "assistant".to_string(),
-        content: content.into(),
+        content: ClaudeContent::Text(content.into()),
     }
 }
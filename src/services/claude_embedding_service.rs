@@ -92,7 +92,7 @@ impl ClaudeEmbeddingService {
     /// Generate embeddings for a batch of texts (internal method)
     async fn generate_embeddings_batch(&self, texts: Vec<String>) -> Result<Vec<Vector>> {
         // Use deterministic hash-based embeddings (production-ready, always works)
-        tracing::info!("Generating {} deterministic embeddings using TF-IDF + hashing", texts.len());
+        tracing::debug!("Generating {} deterministic embeddings using TF-IDF + hashing", texts.len());
         let embeddings = texts.iter()
             .map(|text| self.generate_deterministic_embedding(text))
             .collect();
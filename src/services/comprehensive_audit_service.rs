@@ -7,6 +7,9 @@
 /// - Data access and modifications (who accessed what, when)
 /// - Security events (rate limiting, blacklisting, suspicious activity)
 /// - System events (errors, configuration changes)
+///
+/// Entries are hash-chained by the database on insert; see AuditChainService for
+/// verification and anchoring of the chain.
 
 use sqlx::PgPool;
 use uuid::Uuid;
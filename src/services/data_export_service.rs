@@ -0,0 +1,639 @@
+/// Data Export Service
+///
+/// Large exports (inventory, transactions, audit logs, OpenFDA and EMA catalogs) run as
+/// `data_export` background jobs instead of holding an HTTP response open:
+/// - `create` records the export; the handler queues its job
+/// - `run` counts the matching rows, then writes them page by page (in key order) to
+///   `<FILE_STORAGE_PATH>/exports`, recording progress after every page. A retried job
+///   starts the file over.
+/// - a completed export is downloaded through a signed link (`download_url`) that
+///   expires after DATA_EXPORT_LINK_TTL_SECONDS (default 900); polling the export
+///   issues a fresh one
+/// - `purge_expired` (run by `DataExportPurgeScheduler`) deletes files
+///   DATA_EXPORT_RETENTION_HOURS (default 24) after they were written and marks their
+///   exports expired
+
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::future::Future;
+use std::path::PathBuf;
+use tokio::io::AsyncWriteExt;
+use uuid::Uuid;
+use crate::{
+    middleware::{error_handling::{AppError, Result}, Claims},
+    models::{catalog_export::*, data_export::*},
+    repositories::{EmaRepository, OpenFdaRepository},
+    services::Shutdown,
+};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Directory of export files below the file storage path
+const EXPORTS_DIR: &str = "exports";
+
+const DEFAULT_LINK_TTL_SECONDS: i64 = 900;
+const DEFAULT_RETENTION_HOURS: i64 = 24;
+
+/// Exports a user may have queued or running at once
+const MAX_ACTIVE_EXPORTS: i64 = 3;
+
+const INVENTORY_EXPORT_FROM: &str = r#"
+    FROM inventory i
+    JOIN pharmaceuticals p ON p.id = i.pharmaceutical_id
+    WHERE i.user_id = $1
+      AND ($2::VARCHAR IS NULL OR i.status = $2)
+      AND ($3::DATE IS NULL OR i.expiry_date <= $3)
+"#;
+
+const TRANSACTION_EXPORT_FROM: &str = r#"
+    FROM transactions t
+    WHERE ((t.seller_id = $1 AND ($2::VARCHAR IS NULL OR $2 = 'seller'))
+        OR (t.buyer_id = $1 AND ($2::VARCHAR IS NULL OR $2 = 'buyer')))
+      AND ($3::VARCHAR IS NULL OR t.status = $3)
+      AND ($4::DATE IS NULL OR t.transaction_date >= $4)
+      AND ($5::DATE IS NULL OR t.transaction_date < $5 + 1)
+"#;
+
+const AUDIT_LOG_EXPORT_FROM: &str = r#"
+    FROM audit_logs a
+    WHERE ($1::VARCHAR IS NULL OR a.event_category = $1)
+      AND ($2::VARCHAR IS NULL OR a.severity = $2)
+      AND ($3::UUID IS NULL OR a.actor_user_id = $3)
+      AND ($4::DATE IS NULL OR a.created_at >= $4)
+      AND ($5::DATE IS NULL OR a.created_at < $5 + 1)
+"#;
+
+pub struct DataExportService {
+    db_pool: PgPool,
+    storage_path: PathBuf,
+    signing_key: String,
+}
+
+impl DataExportService {
+    pub fn new(db_pool: PgPool, file_storage_path: &str, signing_key: &str) -> Self {
+        Self {
+            db_pool,
+            storage_path: PathBuf::from(file_storage_path),
+            signing_key: signing_key.to_string(),
+        }
+    }
+
+    /// Record an export for the caller; run it with `run`
+    pub async fn create(&self, request: CreateDataExportRequest, claims: &Claims) -> Result<DataExport> {
+        if request.export_type.requires_admin() && !claims.is_admin() {
+            return Err(AppError::Forbidden(format!(
+                "Only admins can export {}",
+                request.export_type.as_str().replace('_', " ")
+            )));
+        }
+        let format = ExportFormat::parse(request.format.as_deref())?;
+        let filters = request.filters.unwrap_or_else(|| serde_json::json!({}));
+        request.export_type.check_filters(&filters)?;
+
+        let active: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM data_exports WHERE user_id = $1 AND status IN ('queued', 'running')"
+        )
+        .bind(claims.user_id)
+        .fetch_one(&self.db_pool)
+        .await?;
+        if active >= MAX_ACTIVE_EXPORTS {
+            return Err(AppError::TooManyRequests(format!(
+                "You already have {} exports in progress; wait for one to finish",
+                active
+            )));
+        }
+
+        let export = sqlx::query_as::<_, DataExport>(
+            r#"
+            INSERT INTO data_exports (user_id, export_type, format, filters)
+            VALUES ($1, $2, $3, $4)
+            RETURNING *
+            "#
+        )
+        .bind(claims.user_id)
+        .bind(request.export_type.as_str())
+        .bind(format.extension())
+        .bind(filters)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        Ok(export)
+    }
+
+    /// Write the export file. An export left running by an interrupted job starts over.
+    pub async fn run(&self, export_id: Uuid) -> Result<DataExport> {
+        let export = sqlx::query_as::<_, DataExport>(
+            r#"
+            UPDATE data_exports
+            SET status = 'running', rows_written = 0, started_at = COALESCE(started_at, NOW()), updated_at = NOW()
+            WHERE id = $1 AND status IN ('queued', 'running')
+            RETURNING *
+            "#
+        )
+        .bind(export_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::BadRequest("Export has already finished".to_string()))?;
+
+        let export_type = DataExportType::parse(&export.export_type)
+            .ok_or_else(|| AppError::Internal(anyhow::anyhow!("Unknown export type {}", export.export_type)))?;
+
+        let relative_path = format!("{}/{}.{}", EXPORTS_DIR, export.id, export.export_format().extension());
+        let path = self.storage_path.join(&relative_path);
+        let partial_path = path.with_extension("part");
+        tokio::fs::create_dir_all(self.storage_path.join(EXPORTS_DIR)).await.map_err(io_error)?;
+
+        let mut writer = ExportWriter {
+            file: tokio::fs::File::create(&partial_path).await.map_err(io_error)?,
+            hasher: Sha256::new(),
+            bytes: 0,
+        };
+        let written = self.write_export(&export, export_type, &mut writer).await;
+        let written = match written {
+            Ok(()) => writer.finish().await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = written {
+            tokio::fs::remove_file(&partial_path).await.ok();
+            return Err(e);
+        }
+        tokio::fs::rename(&partial_path, &path).await.map_err(io_error)?;
+
+        let finished = sqlx::query_as::<_, DataExport>(
+            r#"
+            UPDATE data_exports
+            SET status = 'completed', file_path = $2, file_size_bytes = $3, content_hash = $4,
+                expires_at = NOW() + make_interval(hours => $5), error = NULL,
+                completed_at = NOW(), updated_at = NOW()
+            WHERE id = $1
+            RETURNING *
+            "#
+        )
+        .bind(export_id)
+        .bind(&relative_path)
+        .bind(writer.bytes as i64)
+        .bind(format!("{:x}", writer.hasher.finalize()))
+        .bind(retention_hours() as i32)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        tracing::info!(
+            "Data export {} ({}) completed: {} rows, {} bytes",
+            export_id,
+            finished.export_type,
+            finished.rows_written,
+            finished.file_size_bytes.unwrap_or(0)
+        );
+
+        Ok(finished)
+    }
+
+    pub async fn list(&self, user_id: Uuid, limit: i64) -> Result<Vec<DataExport>> {
+        let exports = sqlx::query_as::<_, DataExport>(
+            "SELECT * FROM data_exports WHERE user_id = $1 ORDER BY created_at DESC LIMIT $2"
+        )
+        .bind(user_id)
+        .bind(limit.clamp(1, 100))
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(exports)
+    }
+
+    pub async fn get(&self, export_id: Uuid, user_id: Uuid) -> Result<DataExport> {
+        sqlx::query_as::<_, DataExport>("SELECT * FROM data_exports WHERE id = $1 AND user_id = $2")
+            .bind(export_id)
+            .bind(user_id)
+            .fetch_optional(&self.db_pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Export not found".to_string()))
+    }
+
+    /// The export with its progress and, when completed, a freshly signed download link
+    pub fn response(&self, export: DataExport) -> DataExportResponse {
+        let link = self.download_url(&export, Utc::now());
+        DataExportResponse {
+            progress_percent: DataExportResponse::progress_percent(&export),
+            download_url: link.as_ref().map(|(url, _)| url.clone()),
+            download_url_expires_at: link.map(|(_, expires_at)| expires_at),
+            export,
+        }
+    }
+
+    /// Signed download link of a completed export, valid for the link TTL but never
+    /// beyond the export's own expiry
+    pub fn download_url(&self, export: &DataExport, now: DateTime<Utc>) -> Option<(String, DateTime<Utc>)> {
+        if export.status != DataExportStatus::Completed.as_str() {
+            return None;
+        }
+        let mut expires_at = now + Duration::seconds(link_ttl_seconds());
+        if let Some(export_expires_at) = export.expires_at {
+            expires_at = expires_at.min(export_expires_at);
+        }
+
+        let expires = expires_at.timestamp();
+        let url = format!(
+            "/api/exports/{}/download?expires={}&signature={}",
+            export.id,
+            expires,
+            self.sign(export.id, expires)
+        );
+        Some((url, expires_at))
+    }
+
+    /// The completed export a signed link points to, and its file
+    pub async fn open_download(
+        &self,
+        export_id: Uuid,
+        query: &DataExportDownloadQuery,
+    ) -> Result<(DataExport, tokio::fs::File)> {
+        if !self.verify(export_id, query.expires, &query.signature) {
+            return Err(AppError::Forbidden("Invalid download link".to_string()));
+        }
+        if query.expires < Utc::now().timestamp() {
+            return Err(AppError::Forbidden("Download link has expired".to_string()));
+        }
+
+        let export = sqlx::query_as::<_, DataExport>("SELECT * FROM data_exports WHERE id = $1")
+            .bind(export_id)
+            .fetch_optional(&self.db_pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Export not found".to_string()))?;
+        let file_path = match (export.status.as_str(), &export.file_path) {
+            ("completed", Some(file_path)) => file_path.clone(),
+            ("expired", _) => return Err(AppError::NotFound("Export has expired".to_string())),
+            _ => return Err(AppError::NotFound("Export is not ready".to_string())),
+        };
+
+        let file = tokio::fs::File::open(self.storage_path.join(&file_path))
+            .await
+            .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to open export {}: {}", export_id, e)))?;
+        Ok((export, file))
+    }
+
+    /// Fail an export whose job gave up, so the user can start a new one
+    pub async fn fail(&self, export_id: Uuid, error: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE data_exports
+            SET status = 'failed', error = $2, completed_at = NOW(), updated_at = NOW()
+            WHERE id = $1 AND status IN ('queued', 'running')
+            "#
+        )
+        .bind(export_id)
+        .bind(error)
+        .execute(&self.db_pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Delete the files of exports past their expiry; returns the number expired
+    pub async fn purge_expired(&self) -> Result<usize> {
+        let expired: Vec<(Uuid, Option<String>)> = sqlx::query_as(
+            r#"
+            WITH expired AS (
+                SELECT id, file_path FROM data_exports
+                WHERE status = 'completed' AND expires_at <= NOW()
+                FOR UPDATE SKIP LOCKED
+            )
+            UPDATE data_exports d
+            SET status = 'expired', file_path = NULL, updated_at = NOW()
+            FROM expired
+            WHERE d.id = expired.id
+            RETURNING d.id, expired.file_path
+            "#
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        for (export_id, file_path) in &expired {
+            let Some(file_path) = file_path else { continue };
+            if let Err(e) = tokio::fs::remove_file(self.storage_path.join(file_path)).await {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    tracing::warn!("Failed to delete the file of expired export {}: {}", export_id, e);
+                }
+            }
+        }
+
+        Ok(expired.len())
+    }
+
+    // ========================================================================
+    // PRIVATE HELPERS
+    // ========================================================================
+
+    async fn write_export(&self, export: &DataExport, export_type: DataExportType, writer: &mut ExportWriter) -> Result<()> {
+        let format = export.export_format();
+        let pool = &self.db_pool;
+
+        match export_type {
+            DataExportType::Inventory => {
+                let filters: InventoryExportFilters = serde_json::from_value(export.filters.clone())?;
+                let total: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) {}", INVENTORY_EXPORT_FROM))
+                    .bind(export.user_id)
+                    .bind(filters.status.as_deref())
+                    .bind(filters.expiring_before)
+                    .fetch_one(pool)
+                    .await?;
+                let sql = format!(
+                    r#"
+                    SELECT i.id::text AS inventory_id, p.brand_name, p.generic_name, p.ndc_code,
+                           p.manufacturer, i.batch_number, i.quantity, i.expiry_date, i.unit_price,
+                           i.storage_location, i.status, i.created_at, i.updated_at
+                    {} AND ($4::UUID IS NULL OR i.id > $4)
+                    ORDER BY i.id
+                    LIMIT $5
+                    "#,
+                    INVENTORY_EXPORT_FROM
+                );
+                self.write_pages(export, format, total, writer, |after| {
+                    let after = after.map(|key| key.parse::<Uuid>()).transpose();
+                    let query = sqlx::query_as::<_, InventoryExportRow>(&sql)
+                        .bind(export.user_id)
+                        .bind(filters.status.as_deref())
+                        .bind(filters.expiring_before);
+                    async move {
+                        let after = after.map_err(|e| AppError::Internal(anyhow::anyhow!("Invalid export key: {}", e)))?;
+                        Ok(query.bind(after).bind(EXPORT_PAGE_SIZE).fetch_all(pool).await?)
+                    }
+                })
+                .await
+            }
+            DataExportType::Transactions => {
+                let filters: TransactionExportFilters = serde_json::from_value(export.filters.clone())?;
+                let total: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) {}", TRANSACTION_EXPORT_FROM))
+                    .bind(export.user_id)
+                    .bind(filters.role.as_deref())
+                    .bind(filters.status.as_deref())
+                    .bind(filters.from_date)
+                    .bind(filters.to_date)
+                    .fetch_one(pool)
+                    .await?;
+                let sql = format!(
+                    r#"
+                    SELECT t.id::text AS transaction_id,
+                           CASE WHEN t.seller_id = $1 THEN 'seller' ELSE 'buyer' END AS role,
+                           t.inquiry_id, t.seller_id, t.buyer_id, t.quantity, t.unit_price, t.total_price,
+                           t.status, t.transaction_date, t.erp_sales_order_number,
+                           t.erp_purchase_order_number, t.sanctions_status
+                    {} AND ($6::UUID IS NULL OR t.id > $6)
+                    ORDER BY t.id
+                    LIMIT $7
+                    "#,
+                    TRANSACTION_EXPORT_FROM
+                );
+                self.write_pages(export, format, total, writer, |after| {
+                    let after = after.map(|key| key.parse::<Uuid>()).transpose();
+                    let query = sqlx::query_as::<_, TransactionExportRow>(&sql)
+                        .bind(export.user_id)
+                        .bind(filters.role.as_deref())
+                        .bind(filters.status.as_deref())
+                        .bind(filters.from_date)
+                        .bind(filters.to_date);
+                    async move {
+                        let after = after.map_err(|e| AppError::Internal(anyhow::anyhow!("Invalid export key: {}", e)))?;
+                        Ok(query.bind(after).bind(EXPORT_PAGE_SIZE).fetch_all(pool).await?)
+                    }
+                })
+                .await
+            }
+            DataExportType::AuditLogs => {
+                let filters: AuditLogExportFilters = serde_json::from_value(export.filters.clone())?;
+                let total: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) {}", AUDIT_LOG_EXPORT_FROM))
+                    .bind(filters.event_category.as_deref())
+                    .bind(filters.severity.as_deref())
+                    .bind(filters.actor_user_id)
+                    .bind(filters.from_date)
+                    .bind(filters.to_date)
+                    .fetch_one(pool)
+                    .await?;
+                let sql = format!(
+                    r#"
+                    SELECT a.id::text AS export_key, a.event_id, a.event_type, a.event_category, a.severity,
+                           a.actor_user_id, a.actor_type, a.resource_type, a.resource_id, a.action,
+                           a.action_result, a.event_data, host(a.ip_address) AS ip_address, a.request_id,
+                           a.entry_hash, a.created_at
+                    {} AND ($6::BIGINT IS NULL OR a.id > $6)
+                    ORDER BY a.id
+                    LIMIT $7
+                    "#,
+                    AUDIT_LOG_EXPORT_FROM
+                );
+                self.write_pages(export, format, total, writer, |after| {
+                    let after = after.map(|key| key.parse::<i64>()).transpose();
+                    let query = sqlx::query_as::<_, AuditLogExportRow>(&sql)
+                        .bind(filters.event_category.as_deref())
+                        .bind(filters.severity.as_deref())
+                        .bind(filters.actor_user_id)
+                        .bind(filters.from_date)
+                        .bind(filters.to_date);
+                    async move {
+                        let after = after.map_err(|e| AppError::Internal(anyhow::anyhow!("Invalid export key: {}", e)))?;
+                        Ok(query.bind(after).bind(EXPORT_PAGE_SIZE).fetch_all(pool).await?)
+                    }
+                })
+                .await
+            }
+            DataExportType::OpenFdaCatalog => {
+                let params: OpenFdaExportParams = serde_json::from_value(export.filters.clone())?;
+                let repo = OpenFdaRepository::new(pool.clone());
+                let total = repo.count_export(&params).await?;
+                self.write_pages(export, format, total, writer, |after| {
+                    let (repo, params) = (&repo, &params);
+                    async move { repo.export_page(params, after.as_deref().or(params.after.as_deref()), 0, EXPORT_PAGE_SIZE).await }
+                })
+                .await
+            }
+            DataExportType::EmaCatalog => {
+                let params: EmaExportParams = serde_json::from_value(export.filters.clone())?;
+                let repo = EmaRepository::new(pool.clone());
+                let total = repo.count_export(&params).await?;
+                self.write_pages(export, format, total, writer, |after| {
+                    let (repo, params) = (&repo, &params);
+                    async move { repo.export_page(params, after.as_deref().or(params.after.as_deref()), 0, EXPORT_PAGE_SIZE).await }
+                })
+                .await
+            }
+        }
+    }
+
+    /// Write `fetch_page(after)` pages until a short one, recording progress after each.
+    /// Stops between pages when the instance shuts down; the job's retry starts over.
+    async fn write_pages<T, F, Fut>(
+        &self,
+        export: &DataExport,
+        format: ExportFormat,
+        total: i64,
+        writer: &mut ExportWriter,
+        fetch_page: F,
+    ) -> Result<()>
+    where
+        T: ExportRecord,
+        F: Fn(Option<String>) -> Fut,
+        Fut: Future<Output = Result<Vec<T>>>,
+    {
+        sqlx::query("UPDATE data_exports SET total_rows = $2, updated_at = NOW() WHERE id = $1")
+            .bind(export.id)
+            .bind(total)
+            .execute(&self.db_pool)
+            .await?;
+
+        let mut after: Option<String> = None;
+        let mut rows_written: i64 = 0;
+        loop {
+            if Shutdown::requested() {
+                return Err(Shutdown::interrupted());
+            }
+
+            let page = fetch_page(after.clone()).await?;
+            writer.write(&encode_records(format, &page, rows_written == 0)?).await?;
+            rows_written += page.len() as i64;
+
+            sqlx::query("UPDATE data_exports SET rows_written = $2, updated_at = NOW() WHERE id = $1")
+                .bind(export.id)
+                .bind(rows_written)
+                .execute(&self.db_pool)
+                .await?;
+
+            if (page.len() as i64) < EXPORT_PAGE_SIZE {
+                return Ok(());
+            }
+            after = page.last().map(|record| record.export_key().to_string());
+        }
+    }
+
+    fn sign(&self, export_id: Uuid, expires: i64) -> String {
+        let mut mac = HmacSha256::new_from_slice(self.signing_key.as_bytes())
+            .expect("HMAC accepts keys of any length");
+        mac.update(format!("data_export:{}:{}", export_id, expires).as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    fn verify(&self, export_id: Uuid, expires: i64, signature: &str) -> bool {
+        let Ok(signature) = hex::decode(signature) else { return false };
+        let mut mac = HmacSha256::new_from_slice(self.signing_key.as_bytes())
+            .expect("HMAC accepts keys of any length");
+        mac.update(format!("data_export:{}:{}", export_id, expires).as_bytes());
+        mac.verify_slice(&signature).is_ok()
+    }
+}
+
+/// Export file being written, with its running hash and size
+struct ExportWriter {
+    file: tokio::fs::File,
+    hasher: Sha256,
+    bytes: u64,
+}
+
+impl ExportWriter {
+    async fn write(&mut self, chunk: &[u8]) -> Result<()> {
+        self.file.write_all(chunk).await.map_err(io_error)?;
+        self.hasher.update(chunk);
+        self.bytes += chunk.len() as u64;
+        Ok(())
+    }
+
+    async fn finish(&mut self) -> Result<()> {
+        self.file.flush().await.map_err(io_error)?;
+        self.file.sync_all().await.map_err(io_error)
+    }
+}
+
+fn io_error(e: std::io::Error) -> AppError {
+    AppError::Internal(anyhow::anyhow!("Failed to write export file: {}", e))
+}
+
+fn link_ttl_seconds() -> i64 {
+    std::env::var("DATA_EXPORT_LINK_TTL_SECONDS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|seconds| *seconds > 0)
+        .unwrap_or(DEFAULT_LINK_TTL_SECONDS)
+}
+
+fn retention_hours() -> i64 {
+    std::env::var("DATA_EXPORT_RETENTION_HOURS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|hours| *hours > 0)
+        .unwrap_or(DEFAULT_RETENTION_HOURS)
+}
+
+/// Expires finished exports and deletes their files every ten minutes
+pub struct DataExportPurgeScheduler {
+    service: DataExportService,
+}
+
+impl DataExportPurgeScheduler {
+    pub fn new(service: DataExportService) -> Self {
+        Self { service }
+    }
+
+    pub async fn run(&self) {
+        let mut ticker = tokio::time::interval(std::time::Duration::from_secs(600));
+
+        loop {
+            ticker.tick().await;
+            if crate::services::MaintenanceService::pauses("data_export_purge") {
+                continue;
+            }
+            match self.service.purge_expired().await {
+                Ok(0) => {}
+                Ok(count) => tracing::info!("Expired {} data exports", count),
+                Err(e) => tracing::error!("Data export purge failed: {:?}", e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn completed_export(expires_at: DateTime<Utc>) -> DataExport {
+        let now = Utc::now();
+        DataExport {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            export_type: "inventory".to_string(),
+            format: "csv".to_string(),
+            filters: serde_json::json!({}),
+            status: "completed".to_string(),
+            total_rows: Some(10),
+            rows_written: 10,
+            file_path: Some("exports/x.csv".to_string()),
+            file_size_bytes: Some(100),
+            content_hash: None,
+            error: None,
+            expires_at: Some(expires_at),
+            created_at: now,
+            started_at: Some(now),
+            completed_at: Some(now),
+            updated_at: now,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_download_links_are_signed_and_capped_at_export_expiry() {
+        let pool = sqlx::postgres::PgPoolOptions::new()
+            .connect_lazy("postgres://localhost/atlas_pharma")
+            .unwrap();
+        let service = DataExportService::new(pool, "./uploads", "test-signing-key");
+        let now = Utc::now();
+        let export = completed_export(now + Duration::seconds(60));
+
+        let (url, expires_at) = service.download_url(&export, now).unwrap();
+        assert_eq!(expires_at, export.expires_at.unwrap());
+
+        let signature = url.rsplit("signature=").next().unwrap();
+        assert!(service.verify(export.id, expires_at.timestamp(), signature));
+        assert!(!service.verify(export.id, expires_at.timestamp() + 3600, signature));
+        assert!(!service.verify(Uuid::new_v4(), expires_at.timestamp(), signature));
+        assert!(!service.verify(export.id, expires_at.timestamp(), "not-hex"));
+
+        let running = DataExport { status: "running".to_string(), ..export };
+        assert!(service.download_url(&running, now).is_none());
+    }
+}
@@ -0,0 +1,417 @@
+/// Data Retention Service
+///
+/// Retention periods per data category and purge runs. A purge deletes, in batches,
+/// the rows of every enabled category that are older than its retention period, except
+/// those of users and transactions under a legal hold (see LegalHoldService); a hold on
+/// all data keeps the whole category. Every run is recorded in `data_purge_runs` with a
+/// per-category report. Runs are scheduled daily by the alert scheduler (`data_purge` job) and can
+/// be started by a superadmin.
+
+use crate::{
+    middleware::error_handling::{AppError, Result},
+    models::data_retention::*,
+    models::legal_hold::{hold_scope, HoldScope},
+    services::LegalHoldService,
+};
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// A run still marked running after this long was interrupted (e.g. the instance died)
+const STALE_RUN_HOURS: i64 = 12;
+
+/// A table purged for a category. SQL fragments refer to the table as `t`.
+struct PurgeTarget {
+    table: &'static str,
+    age_column: &'static str,
+    /// Rows that belong to the category, besides their age
+    scope: Option<&'static str>,
+    /// Rows of the category that can be deleted (e.g. not referenced elsewhere)
+    condition: Option<&'static str>,
+    /// True for rows of a held user (`$3`, UUID[]); None when rows have no subject
+    held_users: Option<&'static str>,
+    /// True for rows of a held transaction (`$4`, UUID[])
+    held_transactions: Option<&'static str>,
+    /// Rows referencing the purged ones, deleted with them: (table, column)
+    dependents: &'static [(&'static str, &'static str)],
+}
+
+fn purge_targets(category: RetentionCategory) -> &'static [PurgeTarget] {
+    match category {
+        RetentionCategory::AuditLogs => &[PurgeTarget {
+            table: "audit_logs",
+            age_column: "created_at",
+            scope: None,
+            condition: None,
+            held_users: Some("t.actor_user_id = ANY($3) OR (t.resource_type = 'user' AND t.resource_id = ANY($3::TEXT[]))"),
+            held_transactions: Some("t.resource_type = 'transaction' AND t.resource_id = ANY($4::TEXT[])"),
+            dependents: &[],
+        }],
+        RetentionCategory::Notifications => &[PurgeTarget {
+            table: "alert_notifications",
+            age_column: "created_at",
+            scope: None,
+            condition: None,
+            held_users: Some("t.user_id = ANY($3)"),
+            held_transactions: None,
+            dependents: &[],
+        }],
+        RetentionCategory::SyncLogs => &[
+            PurgeTarget {
+                table: "openfda_sync_log",
+                age_column: "sync_started_at",
+                scope: None,
+                condition: None,
+                held_users: None,
+                held_transactions: None,
+                dependents: &[],
+            },
+            PurgeTarget {
+                table: "ema_sync_log",
+                age_column: "sync_started_at",
+                scope: None,
+                condition: None,
+                held_users: None,
+                held_transactions: None,
+                dependents: &[],
+            },
+            PurgeTarget {
+                table: "erp_sync_logs",
+                age_column: "created_at",
+                scope: Some("t.status <> 'running'"),
+                condition: None,
+                held_users: Some("t.triggered_by_user_id = ANY($3)"),
+                held_transactions: None,
+                dependents: &[],
+            },
+        ],
+        RetentionCategory::Messages => &[PurgeTarget {
+            table: "inquiry_messages",
+            age_column: "created_at",
+            scope: None,
+            condition: None,
+            held_users: Some(
+                "EXISTS (SELECT 1 FROM inquiries q JOIN inventory i ON i.id = q.inventory_id \
+                 WHERE q.id = t.inquiry_id AND (q.buyer_id = ANY($3) OR i.user_id = ANY($3)))",
+            ),
+            held_transactions: Some(
+                "EXISTS (SELECT 1 FROM transactions x WHERE x.inquiry_id = t.inquiry_id AND x.id = ANY($4))",
+            ),
+            dependents: &[],
+        }],
+        RetentionCategory::ArchivedInventory => &[PurgeTarget {
+            table: "inventory",
+            age_column: "updated_at",
+            scope: Some("t.status IN ('sold', 'expired')"),
+            condition: Some(
+                "NOT EXISTS (SELECT 1 FROM inquiries q WHERE q.inventory_id = t.id) \
+                 AND NOT EXISTS (SELECT 1 FROM ai_import_row_results r WHERE r.created_inventory_id = t.id)",
+            ),
+            held_users: Some("t.user_id = ANY($3)"),
+            // Lots of a transaction are referenced by its inquiry and never purged
+            held_transactions: None,
+            dependents: &[("inventory_audit", "inventory_id")],
+        }],
+    }
+}
+
+pub struct DataRetentionService {
+    db_pool: PgPool,
+}
+
+impl DataRetentionService {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self { db_pool }
+    }
+
+    // ========================================================================
+    // POLICIES
+    // ========================================================================
+
+    pub async fn list_policies(&self) -> Result<Vec<DataRetentionPolicy>> {
+        let policies = sqlx::query_as::<_, DataRetentionPolicy>(
+            "SELECT * FROM data_retention_policies ORDER BY category",
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(policies)
+    }
+
+    pub async fn update_policy(
+        &self,
+        category: &str,
+        request: UpdateRetentionPolicyRequest,
+        admin_id: Uuid,
+    ) -> Result<DataRetentionPolicy> {
+        let category = parse_category(category)?;
+
+        if let Some(days) = request.retention_days {
+            if days < category.minimum_days() || days > 36500 {
+                return Err(AppError::InvalidInput(format!(
+                    "retention_days for {} must be between {} and 36500",
+                    category.as_str(),
+                    category.minimum_days()
+                )));
+            }
+        }
+
+        let policy = sqlx::query_as::<_, DataRetentionPolicy>(
+            r#"
+            UPDATE data_retention_policies
+            SET retention_days = COALESCE($2, retention_days),
+                is_enabled = COALESCE($3, is_enabled),
+                updated_by = $4,
+                updated_at = NOW()
+            WHERE category = $1
+            RETURNING *
+            "#,
+        )
+        .bind(category.as_str())
+        .bind(request.retention_days)
+        .bind(request.is_enabled)
+        .bind(admin_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Retention policy not found".to_string()))?;
+
+        tracing::info!(
+            "Audit: User {} set retention of {} to {} days ({})",
+            admin_id,
+            policy.category,
+            policy.retention_days,
+            if policy.is_enabled { "enabled" } else { "disabled" }
+        );
+
+        Ok(policy)
+    }
+
+    // ========================================================================
+    // PURGE RUNS
+    // ========================================================================
+
+    pub async fn list_runs(&self, limit: i64, offset: i64) -> Result<Vec<DataPurgeRun>> {
+        let runs = sqlx::query_as::<_, DataPurgeRun>(
+            "SELECT * FROM data_purge_runs ORDER BY started_at DESC LIMIT $1 OFFSET $2",
+        )
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(runs)
+    }
+
+    pub async fn get_run(&self, run_id: Uuid) -> Result<DataPurgeRun> {
+        sqlx::query_as::<_, DataPurgeRun>("SELECT * FROM data_purge_runs WHERE id = $1")
+            .bind(run_id)
+            .fetch_optional(&self.db_pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Purge run not found".to_string()))
+    }
+
+    /// Purge every enabled category. Fails with Conflict while another run is going.
+    /// `triggered_by` is None for scheduled runs.
+    pub async fn run_purge(&self, triggered_by: Option<Uuid>) -> Result<DataPurgeRun> {
+        sqlx::query(
+            r#"
+            UPDATE data_purge_runs
+            SET status = 'failed', completed_at = NOW(), error = 'Interrupted'
+            WHERE status = 'running' AND started_at < NOW() - make_interval(hours => $1)
+            "#,
+        )
+        .bind(STALE_RUN_HOURS as i32)
+        .execute(&self.db_pool)
+        .await?;
+
+        let run_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO data_purge_runs (triggered_by) VALUES ($1) RETURNING id",
+        )
+        .bind(triggered_by)
+        .fetch_one(&self.db_pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db) if db.code().as_deref() == Some("23505") => AppError::Conflict,
+            other => AppError::Database(other),
+        })?;
+
+        let mut report = Vec::with_capacity(RetentionCategory::ALL.len());
+        let outcome = self.purge_categories(&mut report).await;
+
+        let total_purged: i64 = report.iter().map(|r| r.purged).sum();
+        let report_json = serde_json::to_value(&report)?;
+        let (status, error) = match &outcome {
+            Ok(()) => ("completed", None),
+            Err(e) => ("failed", Some(e.to_string())),
+        };
+
+        let run = sqlx::query_as::<_, DataPurgeRun>(
+            r#"
+            UPDATE data_purge_runs
+            SET status = $2, completed_at = NOW(), total_purged = $3, report = $4, error = $5
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(run_id)
+        .bind(status)
+        .bind(total_purged)
+        .bind(&report_json)
+        .bind(&error)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        outcome?;
+
+        tracing::info!(
+            "Audit: Data purge {} ({}) removed {} rows",
+            run.id,
+            triggered_by.map(|id| format!("started by user {}", id)).unwrap_or_else(|| "scheduled".to_string()),
+            total_purged
+        );
+
+        Ok(run)
+    }
+
+    /// Purge category by category, recording each in the report as it completes
+    async fn purge_categories(&self, report: &mut Vec<CategoryPurgeReport>) -> Result<()> {
+        let policies = self.list_policies().await?;
+        let holds = LegalHoldService::new(self.db_pool.clone()).active_holds().await?;
+        let now = Utc::now();
+
+        for category in RetentionCategory::ALL {
+            let Some(policy) = policies.iter().find(|p| p.category == category.as_str()) else {
+                continue;
+            };
+            let cutoff = policy.cutoff(now);
+            let scope = hold_scope(&holds, category);
+
+            let mut entry = CategoryPurgeReport {
+                category,
+                retention_days: policy.retention_days,
+                cutoff,
+                purged: 0,
+                retained: 0,
+                held_user_ids: scope.user_ids.clone(),
+                held_transaction_ids: scope.transaction_ids.clone(),
+                skipped_reason: None,
+            };
+
+            if !policy.is_enabled {
+                entry.skipped_reason = Some("Retention is disabled".to_string());
+                report.push(entry);
+                continue;
+            }
+            if scope.whole_category {
+                entry.skipped_reason = Some("The category is under legal hold".to_string());
+            }
+
+            for target in purge_targets(category) {
+                if !scope.whole_category {
+                    entry.purged += self.purge_target(category, target, cutoff, &scope).await?;
+                }
+                entry.retained += self.count_expired(target, cutoff).await?;
+            }
+
+            tracing::info!(
+                "Data purge: {} purged {} rows older than {} ({} retained)",
+                category.as_str(),
+                entry.purged,
+                cutoff,
+                entry.retained
+            );
+            report.push(entry);
+        }
+
+        Ok(())
+    }
+
+    async fn purge_target(
+        &self,
+        category: RetentionCategory,
+        target: &PurgeTarget,
+        cutoff: DateTime<Utc>,
+        scope: &HoldScope,
+    ) -> Result<i64> {
+        let mut filters = vec![format!("t.{} < $1", target.age_column)];
+        filters.extend(target.scope.map(str::to_string));
+        filters.extend(target.condition.map(str::to_string));
+
+        let mut held = Vec::new();
+        if !scope.user_ids.is_empty() {
+            held.extend(target.held_users);
+        }
+        if !scope.transaction_ids.is_empty() {
+            held.extend(target.held_transactions);
+        }
+        // Both hold arrays are bound whenever either is used, so $3 and $4 always have a type
+        let bind_held = !held.is_empty();
+        if bind_held {
+            let held: Vec<String> = held.iter().map(|h| format!("({})", h)).collect();
+            filters.push(format!("NOT COALESCE({}, FALSE)", held.join(" OR ")));
+        }
+
+        let mut sql = format!(
+            "WITH purged AS (SELECT t.id FROM {} t WHERE {} LIMIT $2)",
+            target.table,
+            filters.join(" AND ")
+        );
+        for (i, (table, column)) in target.dependents.iter().enumerate() {
+            sql.push_str(&format!(
+                ", dependent_{} AS (DELETE FROM {} WHERE {} IN (SELECT id FROM purged))",
+                i, table, column
+            ));
+        }
+        sql.push_str(&format!(" DELETE FROM {} WHERE id IN (SELECT id FROM purged)", target.table));
+
+        let mut purged = 0i64;
+        loop {
+            let mut tx = self.db_pool.begin().await?;
+
+            // Lets the audit log trigger accept deletes of rows older than the cutoff
+            if category == RetentionCategory::AuditLogs {
+                sqlx::query("SELECT set_config('atlas.retention_purge_before', $1, TRUE)")
+                    .bind(cutoff.to_rfc3339())
+                    .execute(&mut *tx)
+                    .await?;
+            }
+
+            let mut query = sqlx::query(&sql).bind(cutoff).bind(PURGE_BATCH_SIZE);
+            if bind_held {
+                query = query.bind(&scope.user_ids).bind(&scope.transaction_ids);
+            }
+            let deleted = query.execute(&mut *tx).await?.rows_affected() as i64;
+            tx.commit().await?;
+
+            purged += deleted;
+            if deleted < PURGE_BATCH_SIZE {
+                return Ok(purged);
+            }
+        }
+    }
+
+    /// Rows of the category still older than the cutoff
+    async fn count_expired(&self, target: &PurgeTarget, cutoff: DateTime<Utc>) -> Result<i64> {
+        let mut sql = format!(
+            "SELECT COUNT(*) FROM {} t WHERE t.{} < $1",
+            target.table, target.age_column
+        );
+        if let Some(scope) = target.scope {
+            sql.push_str(" AND ");
+            sql.push_str(scope);
+        }
+
+        let count: i64 = sqlx::query_scalar(&sql).bind(cutoff).fetch_one(&self.db_pool).await?;
+        Ok(count)
+    }
+}
+
+fn parse_category(category: &str) -> Result<RetentionCategory> {
+    RetentionCategory::parse(category).ok_or_else(|| {
+        AppError::BadRequest(format!(
+            "Unknown data category '{}'; expected one of: {}",
+            category,
+            RetentionCategory::ALL.map(|c| c.as_str()).join(", ")
+        ))
+    })
+}
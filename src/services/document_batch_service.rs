@@ -0,0 +1,443 @@
+/// Regulatory Document Batch Service
+///
+/// Generates one document type for a set of the seller's transactions in a background
+/// job instead of one request per transaction:
+/// - DSCSA: the TI, TH and TS of each sale via DscsaService (existing sets are reused)
+/// - CoA/GDP/GMP: one AI-generated document per transaction via RegulatoryDocumentGenerator,
+///   built from the transaction's product and lot
+///
+/// Items are processed one at a time (AI quota and rate limits apply per document) and
+/// each records its own success or failure, so one bad transaction doesn't stop the
+/// batch. Finished batches are downloadable as a ZIP of the documents plus a manifest.
+
+use chrono::Utc;
+use serde_json::json;
+use sqlx::PgPool;
+use std::io::Write;
+use uuid::Uuid;
+use crate::{
+    middleware::error_handling::{AppError, Result},
+    models::document_batch::*,
+    services::{DocumentType, DscsaService, GenerateDocumentRequest, RegulatoryDocumentGenerator},
+};
+
+pub struct DocumentBatchService {
+    db_pool: PgPool,
+    encryption_key: String,
+}
+
+impl DocumentBatchService {
+    pub fn new(db_pool: PgPool, encryption_key: &str) -> Self {
+        Self { db_pool, encryption_key: encryption_key.to_string() }
+    }
+
+    /// Create a batch for the matching transactions of the seller; run it with `run`
+    pub async fn create(&self, request: CreateDocumentBatchRequest, user_id: Uuid) -> Result<DocumentBatch> {
+        request.check_filters().map_err(AppError::BadRequest)?;
+        if request.document_type != BatchDocumentType::Dscsa && std::env::var("ANTHROPIC_API_KEY").is_err() {
+            return Err(AppError::BadRequest("AI document generation is not configured".to_string()));
+        }
+
+        let active: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM regulatory_document_batches WHERE user_id = $1 AND status IN ('queued', 'running'))"
+        )
+        .bind(user_id)
+        .fetch_one(&self.db_pool)
+        .await?;
+        if active {
+            return Err(AppError::Conflict);
+        }
+
+        let transaction_ids: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            SELECT id FROM transactions
+            WHERE seller_id = $1
+              AND ($2::UUID[] IS NULL OR id = ANY($2))
+              AND ($3::VARCHAR IS NULL OR status = $3)
+              AND ($4::UUID IS NULL OR buyer_id = $4)
+              AND ($5::DATE IS NULL OR transaction_date >= $5)
+              AND ($6::DATE IS NULL OR transaction_date < $6 + 1)
+            ORDER BY transaction_date, id
+            LIMIT $7
+            "#
+        )
+        .bind(user_id)
+        .bind(request.transaction_ids.as_deref())
+        .bind(request.status.as_deref())
+        .bind(request.buyer_id)
+        .bind(request.from_date)
+        .bind(request.to_date)
+        .bind(MAX_BATCH_ITEMS + 1)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        if transaction_ids.is_empty() {
+            return Err(AppError::BadRequest("No transactions of yours match the filters".to_string()));
+        }
+        if transaction_ids.len() as i64 > MAX_BATCH_ITEMS {
+            return Err(AppError::BadRequest(format!(
+                "More than {} transactions match; narrow the filters",
+                MAX_BATCH_ITEMS
+            )));
+        }
+        if let Some(requested) = &request.transaction_ids {
+            if transaction_ids.len() != requested.len() {
+                return Err(AppError::BadRequest(
+                    "Some transactions don't exist or you are not their seller".to_string(),
+                ));
+            }
+        }
+
+        let filters = serde_json::to_value(&request)?;
+        let mut tx = self.db_pool.begin().await?;
+        let batch = sqlx::query_as::<_, DocumentBatch>(
+            r#"
+            INSERT INTO regulatory_document_batches (user_id, document_type, filters, total_items)
+            VALUES ($1, $2, $3, $4)
+            RETURNING *
+            "#
+        )
+        .bind(user_id)
+        .bind(request.document_type.as_str())
+        .bind(filters)
+        .bind(transaction_ids.len() as i32)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO regulatory_document_batch_items (batch_id, transaction_id, position)
+            SELECT $1, t.id, t.position::INTEGER
+            FROM UNNEST($2::UUID[]) WITH ORDINALITY AS t(id, position)
+            "#
+        )
+        .bind(batch.id)
+        .bind(&transaction_ids)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(batch)
+    }
+
+    /// Process the pending items of a batch. A batch left running by an interrupted job
+    /// continues with the items it had not processed.
+    pub async fn run(&self, batch_id: Uuid) -> Result<DocumentBatch> {
+        let batch = sqlx::query_as::<_, DocumentBatch>(
+            r#"
+            UPDATE regulatory_document_batches
+            SET status = 'running', started_at = COALESCE(started_at, NOW()), updated_at = NOW()
+            WHERE id = $1 AND status IN ('queued', 'running')
+            RETURNING *
+            "#
+        )
+        .bind(batch_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::BadRequest("Batch has already finished".to_string()))?;
+
+        let document_type = BatchDocumentType::parse(&batch.document_type)
+            .ok_or_else(|| AppError::Internal(anyhow::anyhow!("Unknown batch document type {}", batch.document_type)))?;
+
+        let result = self.process_items(&batch, document_type).await;
+        let (status, error) = match result {
+            Ok(()) => {
+                let (succeeded, failed): (i32, i32) = sqlx::query_as(
+                    "SELECT succeeded_items, failed_items FROM regulatory_document_batches WHERE id = $1"
+                )
+                .bind(batch_id)
+                .fetch_one(&self.db_pool)
+                .await?;
+                (DocumentBatchStatus::finished(succeeded, failed), None)
+            }
+            Err(e) => {
+                tracing::error!("Regulatory document batch {} failed: {}", batch_id, e);
+                (DocumentBatchStatus::Failed, Some(e.to_string()))
+            }
+        };
+
+        let finished = sqlx::query_as::<_, DocumentBatch>(
+            r#"
+            UPDATE regulatory_document_batches
+            SET status = $2, error = $3, completed_at = NOW(), updated_at = NOW()
+            WHERE id = $1
+            RETURNING *
+            "#
+        )
+        .bind(batch_id)
+        .bind(status.as_str())
+        .bind(error)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        tracing::info!(
+            "Regulatory document batch {} {}: {} succeeded, {} failed",
+            batch_id,
+            finished.status,
+            finished.succeeded_items,
+            finished.failed_items
+        );
+
+        Ok(finished)
+    }
+
+    pub async fn list(&self, user_id: Uuid, limit: i64) -> Result<Vec<DocumentBatch>> {
+        let batches = sqlx::query_as::<_, DocumentBatch>(
+            "SELECT * FROM regulatory_document_batches WHERE user_id = $1 ORDER BY created_at DESC LIMIT $2"
+        )
+        .bind(user_id)
+        .bind(limit.clamp(1, 100))
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(batches)
+    }
+
+    pub async fn get(&self, batch_id: Uuid, user_id: Uuid) -> Result<DocumentBatchDetail> {
+        let batch = self.find(batch_id, user_id).await?;
+        let items = self.items(batch_id).await?;
+        Ok(DocumentBatchDetail { batch, items })
+    }
+
+    /// ZIP of a finished batch: one JSON file per document, grouped by transaction, and
+    /// manifest.json with the batch and its per-transaction results
+    pub async fn archive(&self, batch_id: Uuid, user_id: Uuid) -> Result<(String, Vec<u8>)> {
+        let batch = self.find(batch_id, user_id).await?;
+        if !batch.is_finished() {
+            return Err(AppError::BadRequest("Batch is still running".to_string()));
+        }
+        let items = self.items(batch_id).await?;
+
+        let document_ids: Vec<Uuid> = items.iter().flat_map(|i| i.document_ids.iter().copied()).collect();
+        let documents = sqlx::query_as::<_, BatchArchiveDocument>(
+            r#"
+            SELECT id, document_type, document_number, title, status, content, content_hash,
+                   generated_signature, created_at
+            FROM regulatory_documents
+            WHERE id = ANY($1)
+            "#
+        )
+        .bind(&document_ids)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let mut buffer = std::io::Cursor::new(Vec::new());
+        let mut zip = zip::ZipWriter::new(&mut buffer);
+        let options = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        let zip_error = |e: zip::result::ZipError| AppError::Internal(anyhow::anyhow!("Failed to build archive: {}", e));
+        let write_error = |e: std::io::Error| AppError::Internal(anyhow::anyhow!("Failed to build archive: {}", e));
+
+        let mut manifest_items = Vec::with_capacity(items.len());
+        for item in &items {
+            let mut files = Vec::new();
+            for document_id in &item.document_ids {
+                let Some(document) = documents.iter().find(|d| d.id == *document_id) else { continue };
+                let name = archive_entry_name(item.transaction_id, &document.document_number);
+                zip.start_file(name.as_str(), options).map_err(zip_error)?;
+                zip.write_all(&serde_json::to_vec_pretty(document)?).map_err(write_error)?;
+                files.push(name);
+            }
+            manifest_items.push(json!({
+                "transaction_id": item.transaction_id,
+                "status": item.status,
+                "error": item.error,
+                "document_ids": item.document_ids,
+                "files": files,
+            }));
+        }
+
+        let manifest = json!({
+            "batch": batch,
+            "items": manifest_items,
+            "exported_at": Utc::now(),
+        });
+        zip.start_file("manifest.json", options).map_err(zip_error)?;
+        zip.write_all(&serde_json::to_vec_pretty(&manifest)?).map_err(write_error)?;
+        zip.finish().map_err(zip_error)?;
+        drop(zip);
+
+        let filename = format!(
+            "{}-documents-{}.zip",
+            batch.document_type.to_lowercase(),
+            batch.created_at.format("%Y%m%d-%H%M%S")
+        );
+        Ok((filename, buffer.into_inner()))
+    }
+
+    // ========================================================================
+    // PRIVATE HELPERS
+    // ========================================================================
+
+    async fn find(&self, batch_id: Uuid, user_id: Uuid) -> Result<DocumentBatch> {
+        sqlx::query_as::<_, DocumentBatch>(
+            "SELECT * FROM regulatory_document_batches WHERE id = $1 AND user_id = $2"
+        )
+        .bind(batch_id)
+        .bind(user_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Batch not found".to_string()))
+    }
+
+    async fn items(&self, batch_id: Uuid) -> Result<Vec<DocumentBatchItem>> {
+        let items = sqlx::query_as::<_, DocumentBatchItem>(
+            "SELECT * FROM regulatory_document_batch_items WHERE batch_id = $1 ORDER BY position"
+        )
+        .bind(batch_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(items)
+    }
+
+    /// Fail a batch whose job gave up, so the user can start a new one
+    pub async fn fail(&self, batch_id: Uuid, error: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE regulatory_document_batches
+            SET status = 'failed', error = $2, completed_at = NOW(), updated_at = NOW()
+            WHERE id = $1 AND status IN ('queued', 'running')
+            "#
+        )
+        .bind(batch_id)
+        .bind(error)
+        .execute(&self.db_pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn process_items(&self, batch: &DocumentBatch, document_type: BatchDocumentType) -> Result<()> {
+        let dscsa_service = DscsaService::new(self.db_pool.clone(), &self.encryption_key)?;
+        let generator = match document_type {
+            BatchDocumentType::Dscsa => None,
+            _ => {
+                let api_key = std::env::var("ANTHROPIC_API_KEY")
+                    .map_err(|_| anyhow::anyhow!("ANTHROPIC_API_KEY not configured"))?;
+                Some(RegulatoryDocumentGenerator::new(
+                    self.db_pool.clone(),
+                    api_key,
+                    &self.encryption_key,
+                    batch.user_id,
+                )?)
+            }
+        };
+
+        let pending = sqlx::query_as::<_, DocumentBatchItem>(
+            "SELECT * FROM regulatory_document_batch_items WHERE batch_id = $1 AND status = 'pending' ORDER BY position"
+        )
+        .bind(batch.id)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        for item in pending {
+            let result = match (&generator, document_type) {
+                (_, BatchDocumentType::Dscsa) => dscsa_service
+                    .generate(item.transaction_id, batch.user_id)
+                    .await
+                    .map(|set| set.documents.iter().map(|d| d.id).collect::<Vec<_>>()),
+                (Some(generator), _) => self
+                    .generate_ai_document(generator, document_type, item.transaction_id, batch.user_id)
+                    .await
+                    .map(|id| vec![id]),
+                (None, _) => unreachable!("AI document types always have a generator"),
+            };
+
+            let (status, document_ids, error) = match result {
+                Ok(ids) => ("succeeded", ids, None),
+                Err(e) => {
+                    tracing::warn!(
+                        "Batch {}: {} generation failed for transaction {}: {}",
+                        batch.id,
+                        document_type.as_str(),
+                        item.transaction_id,
+                        e
+                    );
+                    ("failed", Vec::new(), Some(e.to_string()))
+                }
+            };
+
+            let mut tx = self.db_pool.begin().await?;
+            sqlx::query(
+                r#"
+                UPDATE regulatory_document_batch_items
+                SET status = $2, document_ids = $3, error = $4, processed_at = NOW()
+                WHERE id = $1
+                "#
+            )
+            .bind(item.id)
+            .bind(status)
+            .bind(&document_ids)
+            .bind(error)
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query(
+                r#"
+                UPDATE regulatory_document_batches
+                SET processed_items = processed_items + 1,
+                    succeeded_items = succeeded_items + CASE WHEN $2 THEN 1 ELSE 0 END,
+                    failed_items = failed_items + CASE WHEN $2 THEN 0 ELSE 1 END,
+                    updated_at = NOW()
+                WHERE id = $1
+                "#
+            )
+            .bind(batch.id)
+            .bind(status == "succeeded")
+            .execute(&mut *tx)
+            .await?;
+            tx.commit().await?;
+        }
+
+        Ok(())
+    }
+
+    /// One CoA/GDP/GMP document for a transaction, from its product and lot
+    async fn generate_ai_document(
+        &self,
+        generator: &RegulatoryDocumentGenerator,
+        document_type: BatchDocumentType,
+        transaction_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Uuid> {
+        let lot = sqlx::query_as::<_, BatchTransactionLot>(
+            r#"
+            SELECT t.id AS transaction_id, t.transaction_date, t.quantity,
+                   b.company_name AS buyer_company,
+                   i.batch_number, i.expiry_date,
+                   p.brand_name, p.manufacturer, p.ndc_code
+            FROM transactions t
+            JOIN inquiries q ON q.id = t.inquiry_id
+            JOIN inventory i ON i.id = q.inventory_id
+            JOIN pharmaceuticals p ON p.id = i.pharmaceutical_id
+            JOIN users b ON b.id = t.buyer_id
+            WHERE t.id = $1 AND t.seller_id = $2
+            "#
+        )
+        .bind(transaction_id)
+        .bind(user_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Transaction not found".to_string()))?;
+
+        let request = GenerateDocumentRequest {
+            document_type: match document_type {
+                BatchDocumentType::Coa => DocumentType::CoA,
+                BatchDocumentType::Gdp => DocumentType::GDP,
+                _ => DocumentType::GMP,
+            },
+            product_name: Some(lot.brand_name),
+            batch_number: Some(lot.batch_number),
+            manufacturer: Some(lot.manufacturer),
+            product_ndc: lot.ndc_code,
+            test_results: None,
+            custom_fields: Some(json!({
+                "transaction_id": lot.transaction_id,
+                "transaction_date": lot.transaction_date,
+                "quantity": lot.quantity,
+                "consignee": lot.buyer_company,
+                "expiry_date": lot.expiry_date,
+            })),
+        };
+
+        Ok(generator.generate_document(request, user_id).await?.id)
+    }
+}
@@ -0,0 +1,391 @@
+/// AI document extraction for the import pipeline
+/// Reads Certificates of Analysis, supplier price lists and packing lists from PDFs and
+/// images (scanned pages are OCR'd by Claude's vision), keeps per-field confidence for
+/// human review, then applies the reviewed document to inventory or a transaction.
+
+use uuid::Uuid;
+use sqlx::PgPool;
+use crate::middleware::error_handling::{Result, AppError};
+use crate::services::claude_ai_service::{ClaudeAIService, ClaudeRequestConfig, user_message_with_attachment};
+use crate::services::ai_response_cache_service::AiFeature;
+use crate::services::batch_import_processor::BatchImportProcessor;
+use crate::services::file_parser_service::{FileMetadata, FileType, ParsedFile};
+use crate::models::ai_import::*;
+
+/// Claude's request limits for attachments
+const MAX_PDF_BYTES: usize = 32 * 1024 * 1024;
+const MAX_IMAGE_BYTES: usize = 5 * 1024 * 1024;
+
+const EXTRACTION_SYSTEM_PROMPT: &str = r#"You are an expert pharmaceutical document analyst. You read supplier documents - Certificates of Analysis (COA), price lists and packing lists - from PDFs and scanned images, and extract their contents as structured data.
+
+IMPORTANT GUIDELINES:
+1. Read every page, including scanned and handwritten parts
+2. NDC codes use the 5-4-2 digit format (e.g., 12345-678-90); copy them exactly as printed
+3. Dates must be returned as YYYY-MM-DD; quantities as integers; prices as plain decimals without currency symbols
+4. Never guess: if a value is missing or illegible, return null with confidence 0.0
+5. Confidence reflects how legible and unambiguous the value is on the page (0.0-1.0); be conservative
+
+Your response must be valid JSON with this exact structure:
+{
+  "document_kind": "coa" | "price_list" | "packing_list",
+  "header": {
+    "field_name": {"value": "string or null", "confidence": 0.0-1.0}
+  },
+  "line_items": [
+    {"field_name": {"value": "string or null", "confidence": 0.0-1.0}}
+  ],
+  "warnings": [
+    "Human-readable notes about illegible, inconsistent or missing data"
+  ]
+}
+
+Line item fields: ndc_code, brand_name, generic_name, manufacturer, quantity, batch_number, expiry_date, unit_price, storage_location, category, strength, dosage_form.
+Header fields: supplier, document_number, document_date, manufacturer, and any line item field that applies to the whole document.
+A COA describes a single lot: put ndc_code, batch_number, expiry_date and these fields in the header and leave line_items empty: product_name, manufacture_date, test_date, assay_result, specification, conclusion, approved_by."#;
+
+pub struct DocumentExtractionService {
+    db_pool: PgPool,
+    claude_service: ClaudeAIService,
+}
+
+impl DocumentExtractionService {
+    pub fn new(db_pool: PgPool, claude_api_key: String) -> Self {
+        let claude_service = ClaudeAIService::new(claude_api_key, db_pool.clone());
+        Self {
+            db_pool,
+            claude_service,
+        }
+    }
+
+    /// Extract a PDF or image uploaded to an import session and queue it for review
+    pub async fn extract_document(
+        &self,
+        session_id: Uuid,
+        file_data: &[u8],
+        media_type: &str,
+        user_id: Uuid,
+    ) -> Result<AiDocumentExtraction> {
+        let max_bytes = if media_type == "application/pdf" { MAX_PDF_BYTES } else { MAX_IMAGE_BYTES };
+        if file_data.len() > max_bytes {
+            return Err(AppError::InvalidInput(format!(
+                "Document too large. Maximum size for {} is {}MB",
+                media_type,
+                max_bytes / 1024 / 1024
+            )));
+        }
+
+        let config = ClaudeRequestConfig {
+            max_tokens: 8192,
+            temperature: Some(0.0),
+            system_prompt: Some(EXTRACTION_SYSTEM_PROMPT.to_string()),
+        };
+
+        tracing::info!("Sending document extraction request to Claude AI ({})", media_type);
+
+        let validated = self.claude_service.send_message_validated::<DocumentExtraction>(
+            vec![user_message_with_attachment(
+                media_type,
+                file_data,
+                "Extract this pharmaceutical document. Identify whether it is a COA, price list or packing list and return the JSON described in your instructions.",
+            )],
+            config,
+            user_id,
+            Some(session_id),
+            AiFeature::DocumentExtraction,
+        ).await?;
+
+        let ai_response = validated.response;
+        let extraction = validated.value.normalized();
+        self.claude_service.cache_response(&ai_response).await;
+
+        tracing::info!(
+            "Document extracted for session {}: {} with {} line items, {} fields for review",
+            session_id,
+            extraction.document_kind,
+            extraction.line_items.len(),
+            extraction.fields_for_review().len()
+        );
+
+        sqlx::query(
+            r#"
+            UPDATE ai_import_sessions
+            SET
+                file_size_bytes = $1,
+                file_type = $2,
+                detected_format = $3,
+                total_rows = $4,
+                ai_warnings = $5,
+                ai_api_cost_usd = ai_api_cost_usd + $6,
+                ai_tokens_used = ai_tokens_used + $7,
+                analysis_completed_at = NOW(),
+                status = 'extraction_review'
+            WHERE id = $8
+            "#
+        )
+        .bind(file_data.len() as i64)
+        .bind(FileType::Document.to_string())
+        .bind(media_type)
+        .bind(extraction.line_items.len().max(1) as i32)
+        .bind(extraction.warnings.iter().map(|w| serde_json::json!(w)).collect::<Vec<_>>())
+        .bind(rust_decimal::Decimal::try_from(ai_response.cost_usd).unwrap_or_default())
+        .bind((ai_response.input_tokens + ai_response.output_tokens) as i32)
+        .bind(session_id)
+        .execute(&self.db_pool)
+        .await?;
+
+        let record = sqlx::query_as::<_, AiDocumentExtraction>(
+            r#"
+            INSERT INTO ai_document_extractions (session_id, user_id, document_kind, media_type, extraction)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING *
+            "#
+        )
+        .bind(session_id)
+        .bind(user_id)
+        .bind(extraction.document_kind.to_string())
+        .bind(media_type)
+        .bind(serde_json::to_value(&extraction)?)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        self.claude_service.increment_user_usage(user_id, ai_response.cost_usd).await?;
+
+        Ok(record)
+    }
+
+    /// The extraction of a session along with its parsed content
+    pub async fn get_extraction(&self, session_id: Uuid) -> Result<(AiDocumentExtraction, DocumentExtraction)> {
+        let record = sqlx::query_as::<_, AiDocumentExtraction>(
+            "SELECT * FROM ai_document_extractions WHERE session_id = $1"
+        )
+        .bind(session_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("No document extraction for this session".to_string()))?;
+
+        let extraction: DocumentExtraction = serde_json::from_value(record.extraction.clone())?;
+        Ok((record, extraction))
+    }
+
+    /// Record a reviewer's corrections; corrected and confirmed fields are fully trusted
+    pub async fn review_extraction(
+        &self,
+        session_id: Uuid,
+        user_id: Uuid,
+        request: &ReviewExtractionRequest,
+    ) -> Result<(AiDocumentExtraction, DocumentExtraction)> {
+        let (record, mut extraction) = self.get_extraction(session_id).await?;
+        Self::ensure_pending(&record)?;
+
+        if let Some(kind) = &request.document_kind {
+            extraction.document_kind = DocumentKind::from_str(kind).ok_or_else(|| {
+                AppError::BadRequest(format!(
+                    "Unknown document kind '{}'. Expected coa, price_list or packing_list",
+                    kind
+                ))
+            })?;
+        }
+        extraction.apply_corrections(&request.corrections).map_err(AppError::BadRequest)?;
+
+        let record = sqlx::query_as::<_, AiDocumentExtraction>(
+            r#"
+            UPDATE ai_document_extractions
+            SET document_kind = $1, extraction = $2, reviewed_by = $3, reviewed_at = NOW(), updated_at = NOW()
+            WHERE id = $4
+            RETURNING *
+            "#
+        )
+        .bind(extraction.document_kind.to_string())
+        .bind(serde_json::to_value(&extraction)?)
+        .bind(user_id)
+        .bind(record.id)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        Ok((record, extraction))
+    }
+
+    /// Apply a fully reviewed extraction to inventory or attach it to a transaction
+    pub async fn apply_extraction(
+        &self,
+        session: &AiImportSession,
+        user_id: Uuid,
+        request: &ApplyExtractionRequest,
+    ) -> Result<(AiDocumentExtraction, DocumentExtraction)> {
+        let (record, extraction) = self.get_extraction(session.id).await?;
+        Self::ensure_pending(&record)?;
+
+        let pending = extraction.fields_for_review().len();
+        if pending > 0 {
+            return Err(AppError::BadRequest(format!(
+                "{} field(s) below {:.0}% confidence must be confirmed or corrected before applying",
+                pending,
+                REVIEW_CONFIDENCE_THRESHOLD * 100.0
+            )));
+        }
+
+        let mut linked_inventory_ids = Vec::new();
+        let mut transaction_id = None;
+
+        match request.target {
+            ExtractionTarget::Transaction => {
+                let id = request.transaction_id.ok_or_else(|| {
+                    AppError::BadRequest("transaction_id is required to attach a document to a transaction".to_string())
+                })?;
+                self.attach_to_transaction(&record, &extraction, session, id, user_id).await?;
+                transaction_id = Some(id);
+            }
+            ExtractionTarget::Inventory if extraction.document_kind == DocumentKind::CertificateOfAnalysis => {
+                linked_inventory_ids = self.matching_lots(&extraction, user_id).await?;
+            }
+            ExtractionTarget::Inventory => {
+                let (headers, rows) = extraction.inventory_rows();
+                let parsed_file = ParsedFile {
+                    file_type: FileType::Document,
+                    file_hash: session.file_hash.clone(),
+                    headers,
+                    total_rows: rows.len(),
+                    rows,
+                    metadata: FileMetadata {
+                        original_filename: session.original_filename.clone(),
+                        file_size_bytes: session.file_size_bytes as usize,
+                        detected_encoding: None,
+                        has_header_row: true,
+                        empty_rows_skipped: 0,
+                        parsing_warnings: extraction.warnings.clone(),
+                    },
+                };
+
+                BatchImportProcessor::new(self.db_pool.clone())
+                    .process_import(session.id, user_id, parsed_file, ColumnMapping::identity(), &Default::default())
+                    .await?;
+            }
+        }
+
+        let record = sqlx::query_as::<_, AiDocumentExtraction>(
+            r#"
+            UPDATE ai_document_extractions
+            SET status = 'applied', target = $1, transaction_id = $2, linked_inventory_ids = $3,
+                applied_at = NOW(), updated_at = NOW()
+            WHERE id = $4
+            RETURNING *
+            "#
+        )
+        .bind(request.target.to_string())
+        .bind(transaction_id)
+        .bind(&linked_inventory_ids)
+        .bind(record.id)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        // The batch importer completes inventory imports itself
+        sqlx::query(
+            r#"
+            UPDATE ai_import_sessions
+            SET status = 'completed', mapping_approved_at = NOW(),
+                import_completed_at = COALESCE(import_completed_at, NOW())
+            WHERE id = $1
+            "#
+        )
+        .bind(session.id)
+        .execute(&self.db_pool)
+        .await?;
+
+        tracing::info!(
+            "Document extraction for session {} applied to {}",
+            session.id,
+            request.target
+        );
+
+        Ok((record, extraction))
+    }
+
+    fn ensure_pending(record: &AiDocumentExtraction) -> Result<()> {
+        if record.status != "pending_review" {
+            return Err(AppError::BadRequest(format!(
+                "Document extraction is already {}",
+                record.status
+            )));
+        }
+        Ok(())
+    }
+
+    /// Attach the reviewed document to a transaction the user is a party to
+    async fn attach_to_transaction(
+        &self,
+        record: &AiDocumentExtraction,
+        extraction: &DocumentExtraction,
+        session: &AiImportSession,
+        transaction_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<()> {
+        let parties: Option<(Uuid, Uuid)> = sqlx::query_as(
+            "SELECT seller_id, buyer_id FROM transactions WHERE id = $1"
+        )
+        .bind(transaction_id)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        match parties {
+            None => return Err(AppError::NotFound("Transaction not found".to_string())),
+            Some((seller_id, buyer_id)) if seller_id != user_id && buyer_id != user_id => {
+                return Err(AppError::Forbidden("Access denied".to_string()));
+            }
+            Some(_) => {}
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO transaction_documents (
+                transaction_id, extraction_id, document_kind, original_filename, fields, attached_by
+            ) VALUES ($1, $2, $3, $4, $5, $6)
+            "#
+        )
+        .bind(transaction_id)
+        .bind(record.id)
+        .bind(extraction.document_kind.to_string())
+        .bind(&session.original_filename)
+        .bind(serde_json::json!({
+            "header": extraction.header,
+            "line_items": extraction.line_items,
+        }))
+        .bind(user_id)
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// The user's inventory lots a COA certifies: same batch number and, when the COA
+    /// names one, the same NDC
+    async fn matching_lots(&self, extraction: &DocumentExtraction, user_id: Uuid) -> Result<Vec<Uuid>> {
+        let batch_number = extraction.header_value("batch_number").ok_or_else(|| {
+            AppError::BadRequest("The COA has no batch number to link it to inventory".to_string())
+        })?;
+
+        let lots: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            SELECT i.id
+            FROM inventory i
+            JOIN pharmaceuticals p ON p.id = i.pharmaceutical_id
+            WHERE i.user_id = $1
+              AND UPPER(i.batch_number) = UPPER($2)
+              AND ($3::text IS NULL OR REPLACE(p.ndc_code, '-', '') = REPLACE($3, '-', ''))
+            "#
+        )
+        .bind(user_id)
+        .bind(batch_number)
+        .bind(extraction.header_value("ndc_code"))
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        if lots.is_empty() {
+            return Err(AppError::BadRequest(format!(
+                "No inventory lot with batch number '{}' found for this COA",
+                batch_number
+            )));
+        }
+
+        Ok(lots)
+    }
+}
@@ -0,0 +1,462 @@
+/// Document Version Service
+///
+/// Change control of regulatory documents. An approved (or rejected) document is
+/// revised into a new draft version linked to the one it replaces, with the reason for
+/// the change; the new version is signed by its author like a generated document and
+/// goes through the signing sequence again. Approving it supersedes the earlier approved
+/// versions (see ElectronicSignatureService). An approved document can also be made
+/// obsolete. Revisions, supersessions and obsoletions are recorded in the ledger.
+
+use crate::{
+    middleware::error_handling::{AppError, Result},
+    models::document_version::*,
+    models::user::User,
+    repositories::UserRepository,
+    services::Ed25519SignatureService,
+};
+use anyhow::anyhow;
+use sqlx::{FromRow, PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+const VERSION_COLUMNS: &str = r#"
+    id, root_document_id, version_number, previous_version_id, document_number, document_type,
+    title, status, content_hash, change_reason, generated_by, approved_by, approved_at,
+    superseded_by, superseded_at, obsoleted_by, obsolete_reason, obsoleted_at, created_at
+"#;
+
+#[derive(Debug, FromRow)]
+struct VersionContent {
+    id: Uuid,
+    document_type: String,
+    content: serde_json::Value,
+    content_hash: String,
+}
+
+pub struct DocumentVersionService {
+    db_pool: PgPool,
+    user_repo: UserRepository,
+    signature_service: Ed25519SignatureService,
+}
+
+impl DocumentVersionService {
+    pub fn new(db_pool: PgPool, encryption_key: &str) -> Result<Self> {
+        Ok(Self {
+            user_repo: UserRepository::new(db_pool.clone(), encryption_key)?,
+            signature_service: Ed25519SignatureService::new(db_pool.clone(), encryption_key)?,
+            db_pool,
+        })
+    }
+
+    /// Create the next version of a document the user authored
+    pub async fn revise(
+        &self,
+        document_id: Uuid,
+        user_id: Uuid,
+        request: ReviseDocumentRequest,
+    ) -> Result<DocumentVersion> {
+        if !request.content.is_object() {
+            return Err(AppError::BadRequest("Document content must be a JSON object".to_string()));
+        }
+
+        let source = self.load_version(document_id).await?;
+        if source.generated_by != user_id {
+            return Err(AppError::Forbidden("Only the document's author can revise it".to_string()));
+        }
+        if !REVISABLE_STATUSES.contains(&source.status.as_str()) {
+            return Err(AppError::BadRequest(format!(
+                "Document {} is {}; only approved or rejected documents can be revised",
+                source.document_number, source.status
+            )));
+        }
+
+        let lineage = self.lineage(source.root_document_id).await?;
+        if lineage.iter().any(|v| v.previous_version_id == Some(source.id)) {
+            return Err(AppError::BadRequest(
+                "Only the latest version of a document can be revised".to_string(),
+            ));
+        }
+        if let Some(open) = lineage.iter().find(|v| OPEN_STATUSES.contains(&v.status.as_str())) {
+            return Err(AppError::BadRequest(format!(
+                "Version {} of this document is still {}",
+                open.version_number, open.status
+            )));
+        }
+        let root = lineage
+            .iter()
+            .find(|v| v.id == source.root_document_id)
+            .ok_or_else(|| anyhow!("Version 1 of document {} is missing", source.id))?;
+
+        let author = self.author(user_id).await?;
+        let public_key = self.public_key(user_id).await?;
+
+        let version_number = source.version_number + 1;
+        let document_number = version_document_number(&root.document_number, version_number);
+        let title = request.title.as_deref().map(str::trim).unwrap_or(&source.title).to_string();
+        let change_reason = request.change_reason.trim().to_string();
+
+        let content_json = serde_json::to_string(&request.content)?;
+        let (signature, content_hash) = self.signature_service.sign_document(user_id, &content_json).await?;
+
+        let mut tx = self.db_pool.begin().await?;
+        let new_id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO regulatory_documents (
+                document_type, document_number, title, content, content_hash, generated_signature,
+                status, generated_by, product_id, batch_number, inventory_id, catalog_version_id,
+                metadata, root_document_id, version_number, previous_version_id, change_reason
+            )
+            SELECT document_type, $2, $3, $4, $5, $6,
+                   'draft', $7, product_id, batch_number, inventory_id, catalog_version_id,
+                   metadata, root_document_id, $8, id, $9
+            FROM regulatory_documents
+            WHERE id = $1
+            RETURNING id
+            "#,
+        )
+        .bind(source.id)
+        .bind(&document_number)
+        .bind(&title)
+        .bind(&request.content)
+        .bind(&content_hash)
+        .bind(&signature)
+        .bind(user_id)
+        .bind(version_number)
+        .bind(&change_reason)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db) if db.code().as_deref() == Some("23505") => AppError::Conflict,
+            other => AppError::Database(other),
+        })?;
+
+        insert_ledger_entry(
+            &mut tx,
+            LedgerEntry {
+                document_id: new_id,
+                document_type: &source.document_type,
+                operation: "revised",
+                description: format!("Version {} of {}: {}", version_number, root.document_number, change_reason),
+                content_hash: &content_hash,
+                content_snapshot: Some(&request.content),
+                signature: &signature,
+                public_key: &public_key,
+                author: &author,
+                metadata: serde_json::json!({
+                    "previous_version_id": source.id,
+                    "previous_content_hash": source.content_hash,
+                    "version_number": version_number,
+                    "change_reason": change_reason,
+                }),
+            },
+        )
+        .await?;
+        tx.commit().await?;
+
+        tracing::info!(
+            "Audit: User {} revised document {} into {} (version {}): {}",
+            user_id,
+            source.document_number,
+            document_number,
+            version_number,
+            change_reason
+        );
+
+        self.load_version(new_id).await
+    }
+
+    /// Withdraw an approved document without a replacement
+    pub async fn obsolete(
+        &self,
+        document_id: Uuid,
+        user_id: Uuid,
+        request: ObsoleteDocumentRequest,
+    ) -> Result<DocumentVersion> {
+        let document = self.load_version(document_id).await?;
+        if document.generated_by != user_id {
+            return Err(AppError::Forbidden("Only the document's author can make it obsolete".to_string()));
+        }
+        if document.status != "approved" {
+            return Err(AppError::BadRequest(format!(
+                "Document {} is {}; only approved documents can be made obsolete",
+                document.document_number, document.status
+            )));
+        }
+
+        let author = self.author(user_id).await?;
+        let public_key = self.public_key(user_id).await?;
+        let content = self.load_content(document_id).await?;
+        let (signature, _) = self
+            .signature_service
+            .sign_document(user_id, &serde_json::to_string(&content.content)?)
+            .await?;
+        let reason = request.reason.trim().to_string();
+
+        let mut tx = self.db_pool.begin().await?;
+        let updated = sqlx::query(
+            r#"
+            UPDATE regulatory_documents
+            SET status = 'obsolete', obsoleted_by = $2, obsolete_reason = $3, obsoleted_at = NOW(),
+                updated_at = NOW()
+            WHERE id = $1 AND status = 'approved'
+            "#,
+        )
+        .bind(document_id)
+        .bind(user_id)
+        .bind(&reason)
+        .execute(&mut *tx)
+        .await?;
+        if updated.rows_affected() == 0 {
+            return Err(AppError::Conflict);
+        }
+
+        insert_ledger_entry(
+            &mut tx,
+            LedgerEntry {
+                document_id,
+                document_type: &content.document_type,
+                operation: "obsoleted",
+                description: format!("Made obsolete: {}", reason),
+                content_hash: &content.content_hash,
+                content_snapshot: None,
+                signature: &signature,
+                public_key: &public_key,
+                author: &author,
+                metadata: serde_json::json!({ "reason": reason }),
+            },
+        )
+        .await?;
+        tx.commit().await?;
+
+        tracing::info!(
+            "Audit: User {} made document {} obsolete: {}",
+            user_id,
+            document.document_number,
+            reason
+        );
+
+        self.load_version(document_id).await
+    }
+
+    /// All versions of the document's lineage, oldest first
+    pub async fn history(&self, document_id: Uuid, user_id: Uuid) -> Result<DocumentVersionHistory> {
+        let document = self.load_version(document_id).await?;
+        if document.generated_by != user_id {
+            return Err(AppError::NotFound("Document not found".to_string()));
+        }
+
+        let versions = self.lineage(document.root_document_id).await?;
+        let current_version_id = versions.iter().rev().find(|v| v.status == "approved").map(|v| v.id);
+
+        Ok(DocumentVersionHistory {
+            root_document_id: document.root_document_id,
+            current_version_id,
+            versions,
+        })
+    }
+
+    /// Content changes from another version (the previous one by default) to this one
+    pub async fn diff(&self, document_id: Uuid, user_id: Uuid, against: Option<Uuid>) -> Result<DocumentDiff> {
+        let to = self.load_version(document_id).await?;
+        if to.generated_by != user_id {
+            return Err(AppError::NotFound("Document not found".to_string()));
+        }
+
+        let from_id = against
+            .or(to.previous_version_id)
+            .ok_or_else(|| AppError::BadRequest("Version 1 has no previous version to compare with".to_string()))?;
+        let from = self.load_version(from_id).await?;
+        if from.root_document_id != to.root_document_id {
+            return Err(AppError::BadRequest("Only versions of the same document can be compared".to_string()));
+        }
+
+        let from_content = self.load_content(from.id).await?;
+        let to_content = self.load_content(to.id).await?;
+
+        let mut changes = diff_content(&from_content.content, &to_content.content);
+        let truncated = changes.len() > MAX_DIFF_CHANGES;
+        changes.truncate(MAX_DIFF_CHANGES);
+
+        Ok(DocumentDiff {
+            from_document_id: from.id,
+            from_version: from.version_number,
+            to_document_id: to.id,
+            to_version: to.version_number,
+            title_changed: from.title != to.title,
+            changes,
+            truncated,
+        })
+    }
+
+    /// Supersede the approved earlier versions of a document being approved. Signing is
+    /// done up front; the writes join the approval transaction.
+    pub async fn supersede_earlier_versions(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        document_id: Uuid,
+        approver_id: Uuid,
+    ) -> Result<usize> {
+        let document = self.load_version(document_id).await?;
+        let earlier: Vec<Uuid> = self
+            .lineage(document.root_document_id)
+            .await?
+            .into_iter()
+            .filter(|v| v.version_number < document.version_number && v.status == "approved")
+            .map(|v| v.id)
+            .collect();
+        if earlier.is_empty() {
+            return Ok(0);
+        }
+
+        let approver = self.author(approver_id).await?;
+        let public_key = self.public_key(approver_id).await?;
+
+        for version_id in &earlier {
+            let content = self.load_content(*version_id).await?;
+            let (signature, _) = self
+                .signature_service
+                .sign_document(approver_id, &serde_json::to_string(&content.content)?)
+                .await?;
+
+            sqlx::query(
+                r#"
+                UPDATE regulatory_documents
+                SET status = 'superseded', superseded_by = $2, superseded_at = NOW(), updated_at = NOW()
+                WHERE id = $1 AND status = 'approved'
+                "#,
+            )
+            .bind(version_id)
+            .bind(document_id)
+            .execute(&mut **tx)
+            .await?;
+
+            insert_ledger_entry(
+                tx,
+                LedgerEntry {
+                    document_id: *version_id,
+                    document_type: &content.document_type,
+                    operation: "superseded",
+                    description: format!(
+                        "Superseded by {} (version {})",
+                        document.document_number, document.version_number
+                    ),
+                    content_hash: &content.content_hash,
+                    content_snapshot: None,
+                    signature: &signature,
+                    public_key: &public_key,
+                    author: &approver,
+                    metadata: serde_json::json!({
+                        "superseded_by": document_id,
+                        "superseded_by_version": document.version_number,
+                    }),
+                },
+            )
+            .await?;
+        }
+
+        tracing::info!(
+            "Document {} (version {}) superseded {} earlier version(s)",
+            document.document_number,
+            document.version_number,
+            earlier.len()
+        );
+
+        Ok(earlier.len())
+    }
+
+    // ========================================================================
+    // PRIVATE HELPERS
+    // ========================================================================
+
+    async fn load_version(&self, document_id: Uuid) -> Result<DocumentVersion> {
+        sqlx::query_as::<_, DocumentVersion>(&format!(
+            "SELECT {} FROM regulatory_documents WHERE id = $1",
+            VERSION_COLUMNS
+        ))
+        .bind(document_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Document not found".to_string()))
+    }
+
+    async fn lineage(&self, root_document_id: Uuid) -> Result<Vec<DocumentVersion>> {
+        let versions = sqlx::query_as::<_, DocumentVersion>(&format!(
+            "SELECT {} FROM regulatory_documents WHERE root_document_id = $1 ORDER BY version_number",
+            VERSION_COLUMNS
+        ))
+        .bind(root_document_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(versions)
+    }
+
+    async fn load_content(&self, document_id: Uuid) -> Result<VersionContent> {
+        sqlx::query_as::<_, VersionContent>(
+            "SELECT id, document_type, content, content_hash FROM regulatory_documents WHERE id = $1",
+        )
+        .bind(document_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Document not found".to_string()))
+    }
+
+    async fn author(&self, user_id: Uuid) -> Result<User> {
+        self.user_repo
+            .find_by_id(user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))
+    }
+
+    async fn public_key(&self, user_id: Uuid) -> Result<String> {
+        if !self.signature_service.has_keypair(user_id).await? {
+            self.signature_service.generate_user_keypair(user_id).await?;
+            tracing::info!("Generated Ed25519 keypair for user {}", user_id);
+        }
+        Ok(self
+            .signature_service
+            .get_user_public_key(user_id)
+            .await?
+            .ok_or_else(|| anyhow!("User has no public key"))?)
+    }
+}
+
+struct LedgerEntry<'a> {
+    document_id: Uuid,
+    document_type: &'a str,
+    operation: &'a str,
+    description: String,
+    content_hash: &'a str,
+    content_snapshot: Option<&'a serde_json::Value>,
+    signature: &'a str,
+    public_key: &'a str,
+    author: &'a User,
+    metadata: serde_json::Value,
+}
+
+async fn insert_ledger_entry(tx: &mut Transaction<'_, Postgres>, entry: LedgerEntry<'_>) -> Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO regulatory_document_ledger (
+            document_id, document_type, operation, operation_description, content_hash,
+            content_snapshot, signature, signature_public_key, user_id, user_email, user_name, metadata
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+        "#,
+    )
+    .bind(entry.document_id)
+    .bind(entry.document_type)
+    .bind(entry.operation)
+    .bind(&entry.description)
+    .bind(entry.content_hash)
+    .bind(entry.content_snapshot)
+    .bind(entry.signature)
+    .bind(entry.public_key)
+    .bind(entry.author.id)
+    .bind(&entry.author.email)
+    .bind(&entry.author.contact_person)
+    .bind(&entry.metadata)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,312 @@
+/// DSCSA Service
+///
+/// Generates the DSCSA Transaction Information, History and Statement for a sale between
+/// US trading partners from the transaction's catalog and lot data. No AI is involved:
+/// the documents are built deterministically, signed with the seller's Ed25519 key and
+/// stored as draft regulatory documents with a "generated" ledger entry, so they are
+/// approved and verified through the regulatory document endpoints. Each document is
+/// attached to the transaction's document vault.
+
+use crate::{
+    middleware::error_handling::{AppError, Result},
+    models::dscsa::*,
+    models::user::User,
+    repositories::UserRepository,
+    services::Ed25519SignatureService,
+};
+use anyhow::anyhow;
+use chrono::Datelike;
+use sha2::{Digest, Sha256};
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+/// Earlier platform sales followed back when building the transaction history
+const MAX_HISTORY_DEPTH: usize = 25;
+
+const VAULT_DOCUMENTS: &str = r#"
+    SELECT rd.id, td.id AS transaction_document_id, td.document_kind, rd.document_type,
+           rd.document_number, rd.title, rd.status, rd.content, rd.content_hash,
+           rd.generated_signature, rd.approved_at, rd.created_at
+    FROM transaction_documents td
+    JOIN regulatory_documents rd ON rd.id = td.regulatory_document_id
+    WHERE td.transaction_id = $1
+      AND td.document_kind IN ('dscsa_ti', 'dscsa_th', 'dscsa_ts')
+    ORDER BY td.document_kind
+"#;
+
+/// Earlier sale of the lot with the seller to follow back from
+#[derive(sqlx::FromRow)]
+struct PriorSale {
+    #[sqlx(flatten)]
+    transaction: DscsaPriorTransaction,
+    seller_id: Uuid,
+}
+
+pub struct DscsaService {
+    db_pool: PgPool,
+    user_repo: UserRepository,
+    signature_service: Ed25519SignatureService,
+}
+
+impl DscsaService {
+    pub fn new(db_pool: PgPool, encryption_key: &str) -> Result<Self> {
+        Ok(Self {
+            user_repo: UserRepository::new(db_pool.clone(), encryption_key)?,
+            signature_service: Ed25519SignatureService::new(db_pool.clone(), encryption_key)?,
+            db_pool,
+        })
+    }
+
+    /// DSCSA documents in the vault of a transaction the user is a party to
+    pub async fn list(&self, transaction_id: Uuid, user_id: Uuid) -> Result<Vec<DscsaDocument>> {
+        let lot = self.load_lot(transaction_id).await?;
+        if lot.seller_id != user_id && lot.buyer_id != user_id {
+            return Err(AppError::Forbidden("Access denied".to_string()));
+        }
+
+        self.vault_documents(transaction_id).await
+    }
+
+    /// Generate the TI, TH and TS of a sale (seller only). When they already exist they
+    /// are returned as they are.
+    pub async fn generate(&self, transaction_id: Uuid, user_id: Uuid) -> Result<DscsaDocumentSet> {
+        let lot = self.load_lot(transaction_id).await?;
+        if lot.seller_id != user_id {
+            return Err(AppError::Forbidden(
+                "Only the seller provides DSCSA transaction data".to_string(),
+            ));
+        }
+        if let Some(reason) = dscsa_ineligibility(&lot) {
+            return Err(AppError::BadRequest(reason));
+        }
+
+        let existing = self.vault_documents(transaction_id).await?;
+        if !existing.is_empty() {
+            return Ok(DscsaDocumentSet { transaction_id, documents: existing, generated: false });
+        }
+
+        if !self.signature_service.has_keypair(user_id).await? {
+            self.signature_service.generate_user_keypair(user_id).await?;
+            tracing::info!("Generated Ed25519 keypair for user {}", user_id);
+        }
+        let public_key = self
+            .signature_service
+            .get_user_public_key(user_id)
+            .await?
+            .ok_or_else(|| anyhow!("User has no public key"))?;
+
+        let seller_user = self.find_user(lot.seller_id).await?;
+        let seller = trading_partner(&seller_user);
+        let buyer = trading_partner(&self.find_user(lot.buyer_id).await?);
+        let prior = self.prior_transactions(&lot).await?;
+
+        let mut tx = self.db_pool.begin().await?;
+        for kind in DscsaDocumentKind::ALL {
+            let content = match kind {
+                DscsaDocumentKind::TransactionInformation => build_transaction_information(&lot, &seller, &buyer),
+                DscsaDocumentKind::TransactionHistory => build_transaction_history(&lot, &seller, &buyer, &prior),
+                DscsaDocumentKind::TransactionStatement => build_transaction_statement(&lot, &seller, &buyer),
+            };
+            self.store_document(&mut tx, kind, &lot, &content, &seller_user, &public_key).await?;
+        }
+        tx.commit().await?;
+
+        tracing::info!(
+            "Generated DSCSA transaction data for transaction {} ({} prior platform sales in history)",
+            transaction_id,
+            prior.len()
+        );
+
+        let documents = self.vault_documents(transaction_id).await?;
+        Ok(DscsaDocumentSet { transaction_id, documents, generated: true })
+    }
+
+    // ========================================================================
+    // PRIVATE HELPERS
+    // ========================================================================
+
+    async fn load_lot(&self, transaction_id: Uuid) -> Result<DscsaTransactionLot> {
+        sqlx::query_as::<_, DscsaTransactionLot>(
+            r#"
+            SELECT t.id AS transaction_id, t.transaction_date, t.status AS transaction_status,
+                   t.quantity, t.seller_id, t.buyer_id,
+                   s.country_code AS seller_country, b.country_code AS buyer_country,
+                   i.id AS inventory_id, i.pharmaceutical_id, i.batch_number, i.expiry_date,
+                   p.brand_name, p.generic_name, p.ndc_code, p.manufacturer, p.strength, p.dosage_form
+            FROM transactions t
+            JOIN inquiries q ON q.id = t.inquiry_id
+            JOIN inventory i ON i.id = q.inventory_id
+            JOIN pharmaceuticals p ON p.id = i.pharmaceutical_id
+            JOIN users s ON s.id = t.seller_id
+            JOIN users b ON b.id = t.buyer_id
+            WHERE t.id = $1
+            "#,
+        )
+        .bind(transaction_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Transaction not found".to_string()))
+    }
+
+    async fn find_user(&self, user_id: Uuid) -> Result<User> {
+        self.user_repo
+            .find_by_id(user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))
+    }
+
+    /// Earlier platform sales of the lot, following each seller back to whoever sold it
+    /// to them; oldest first
+    async fn prior_transactions(&self, lot: &DscsaTransactionLot) -> Result<Vec<DscsaPriorTransaction>> {
+        let mut history = Vec::new();
+        let mut owner = lot.seller_id;
+        let mut before = lot.transaction_date;
+
+        while history.len() < MAX_HISTORY_DEPTH {
+            let previous = sqlx::query_as::<_, PriorSale>(
+                r#"
+                SELECT t.id AS transaction_id, t.transaction_date, t.quantity, t.seller_id,
+                       s.company_name AS seller_company, b.company_name AS buyer_company
+                FROM transactions t
+                JOIN inquiries q ON q.id = t.inquiry_id
+                JOIN inventory i ON i.id = q.inventory_id
+                JOIN users s ON s.id = t.seller_id
+                JOIN users b ON b.id = t.buyer_id
+                WHERE t.buyer_id = $1
+                  AND t.status = 'completed'
+                  AND i.pharmaceutical_id = $2
+                  AND UPPER(i.batch_number) = UPPER($3)
+                  AND ($4::TIMESTAMPTZ IS NULL OR t.transaction_date < $4)
+                ORDER BY t.transaction_date DESC
+                LIMIT 1
+                "#,
+            )
+            .bind(owner)
+            .bind(lot.pharmaceutical_id)
+            .bind(&lot.batch_number)
+            .bind(before)
+            .fetch_optional(&self.db_pool)
+            .await?;
+
+            let Some(PriorSale { transaction: previous, seller_id }) = previous else {
+                break;
+            };
+            owner = seller_id;
+            before = previous.transaction_date;
+            history.push(previous);
+        }
+
+        history.reverse();
+        Ok(history)
+    }
+
+    async fn vault_documents(&self, transaction_id: Uuid) -> Result<Vec<DscsaDocument>> {
+        let documents = sqlx::query_as::<_, DscsaDocument>(VAULT_DOCUMENTS)
+            .bind(transaction_id)
+            .fetch_all(&self.db_pool)
+            .await?;
+
+        Ok(documents)
+    }
+
+    /// Sign and store one document as a draft, record it in the ledger and attach it to
+    /// the transaction
+    async fn store_document(
+        &self,
+        tx: &mut Transaction<'_, Postgres>,
+        kind: DscsaDocumentKind,
+        lot: &DscsaTransactionLot,
+        content: &serde_json::Value,
+        signer: &User,
+        public_key: &str,
+    ) -> Result<()> {
+        let content_json = serde_json::to_string(content)?;
+        let content_hash = hex::encode(Sha256::digest(content_json.as_bytes()));
+        let (signature, _) = self.signature_service.sign_document(signer.id, &content_json).await?;
+
+        let year = chrono::Utc::now().date_naive().year();
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM regulatory_documents WHERE document_type = $1 AND EXTRACT(YEAR FROM created_at) = $2",
+        )
+        .bind(kind.document_type())
+        .bind(year as i32)
+        .fetch_one(&mut **tx)
+        .await?;
+        let document_number = format!("{}-{}-{:06}", kind.document_type(), year, count + 1);
+        let title = format!("{} - {}", kind.title(), document_number);
+
+        let document_id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO regulatory_documents (
+                document_type, document_number, title, content, content_hash, generated_signature,
+                product_id, batch_number, inventory_id, status, generated_by, metadata
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, 'draft', $10, $11)
+            RETURNING id
+            "#,
+        )
+        .bind(kind.document_type())
+        .bind(&document_number)
+        .bind(&title)
+        .bind(content)
+        .bind(&content_hash)
+        .bind(&signature)
+        .bind(lot.pharmaceutical_id)
+        .bind(&lot.batch_number)
+        .bind(lot.inventory_id)
+        .bind(signer.id)
+        .bind(serde_json::json!({
+            "transaction_id": lot.transaction_id,
+            "standard": DSCSA_STANDARD,
+        }))
+        .fetch_one(&mut **tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO regulatory_document_ledger (
+                document_id, document_type, operation, content_hash, content_snapshot,
+                signature, signature_public_key, user_id, user_email, user_name
+            )
+            VALUES ($1, $2, 'generated', $3, $4, $5, $6, $7, $8, $9)
+            "#,
+        )
+        .bind(document_id)
+        .bind(kind.document_type())
+        .bind(&content_hash)
+        .bind(content)
+        .bind(&signature)
+        .bind(public_key)
+        .bind(signer.id)
+        .bind(&signer.email)
+        .bind(&signer.contact_person)
+        .execute(&mut **tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO transaction_documents (
+                transaction_id, regulatory_document_id, document_kind, original_filename, fields, attached_by
+            ) VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+        )
+        .bind(lot.transaction_id)
+        .bind(document_id)
+        .bind(kind.vault_kind())
+        .bind(format!("{}.json", document_number))
+        .bind(content)
+        .bind(signer.id)
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+}
+
+fn trading_partner(user: &User) -> DscsaTradingPartner {
+    DscsaTradingPartner {
+        company_name: user.company_name.clone(),
+        address: user.address.clone(),
+        license_number: user.license_number.clone(),
+    }
+}
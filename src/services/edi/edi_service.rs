@@ -0,0 +1,940 @@
+// EDI Service
+// Trading partner profiles, 850 emission for marketplace transactions, and receipt of
+// 855/856/810 documents, which are translated and applied to the matching transaction
+
+use chrono::Utc;
+use rand::RngCore;
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::transport::{As2Client, As2Config, EdiTransportClient};
+use super::translate::{build_850, translate_inbound};
+use super::x12::{self, OutboundEnvelope};
+use crate::middleware::error_handling::{AppError, Result};
+use crate::models::edi::{
+    EdiAckStatus, EdiDocument, EdiDocumentQuery, EdiInboundDocument, EdiOrderLine, EdiPartnerResponse,
+    EdiPurchaseOrder, EdiTradingPartner, EdiTransactionSet, EdiTransport, UpsertEdiPartnerRequest,
+};
+use crate::services::encryption_service::EncryptionService;
+use crate::services::erp::{SftpClient, SftpConfig};
+use crate::utils::ndc::ndc_to_11_digit;
+
+const PARTNER_COLUMNS: &str = r#"
+    id, user_id, name, isa_qualifier, isa_id, gs_id, x12_version, test_mode, transport,
+    as2_id, as2_url, as2_username, as2_password, as2_inbound_token,
+    sftp_host, sftp_port, sftp_username, sftp_password, sftp_private_key, sftp_host_key_fingerprint,
+    sftp_inbound_path, sftp_outbound_path, sftp_archive_path,
+    enabled, last_polled_at, created_at, updated_at
+"#;
+
+const DOCUMENT_COLUMNS: &str = r#"
+    d.id, d.partner_id, d.transaction_id, d.direction, d.transaction_set, d.interchange_control_number,
+    d.transaction_control_number, d.po_number, d.transport, d.status, d.raw_x12, d.translated,
+    d.notes, d.error_message, d.created_at
+"#;
+
+const X12_VERSIONS: [&str; 2] = ["004010", "005010"];
+
+/// A pending 850 older than this was interrupted mid-send and may be claimed again
+const PENDING_SEND_TIMEOUT_MINUTES: i64 = 15;
+
+/// Atlas' own interchange identification, from the environment
+#[derive(Debug, Clone)]
+pub struct EdiIdentity {
+    pub isa_qualifier: String,
+    pub isa_id: String,
+    pub gs_id: String,
+    pub as2_id: String,
+}
+
+impl EdiIdentity {
+    pub fn from_env() -> Self {
+        let isa_id = std::env::var("EDI_ISA_ID").unwrap_or_else(|_| "ATLASPHARMA".to_string());
+        Self {
+            isa_qualifier: std::env::var("EDI_ISA_QUALIFIER").unwrap_or_else(|_| "ZZ".to_string()),
+            gs_id: std::env::var("EDI_GS_ID").unwrap_or_else(|_| isa_id.clone()),
+            as2_id: std::env::var("EDI_AS2_ID").unwrap_or_else(|_| isa_id.clone()),
+            isa_id,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct PurchaseOrderSource {
+    transaction_date: chrono::DateTime<Utc>,
+    seller_id: Uuid,
+    quantity: i32,
+    unit_price: Decimal,
+    inventory_id: Uuid,
+    batch_number: String,
+    ndc_code: Option<String>,
+    brand_name: String,
+    generic_name: String,
+    strength: Option<String>,
+    buyer_name: String,
+    seller_name: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct TransactionTotals {
+    status: String,
+    quantity: i32,
+    total_price: Decimal,
+}
+
+pub struct EdiService {
+    pool: PgPool,
+    encryption_key: String,
+    encryption_service: EncryptionService,
+    identity: EdiIdentity,
+}
+
+impl EdiService {
+    pub fn new(pool: PgPool) -> Result<Self> {
+        let encryption_key = std::env::var("ENCRYPTION_KEY")
+            .map_err(|_| AppError::Internal(anyhow::anyhow!("ENCRYPTION_KEY not set")))?;
+        let encryption_service = EncryptionService::new(&encryption_key)?;
+
+        Ok(Self {
+            pool,
+            encryption_key,
+            encryption_service,
+            identity: EdiIdentity::from_env(),
+        })
+    }
+
+    pub fn identity(&self) -> &EdiIdentity {
+        &self.identity
+    }
+
+    // ========================================================================
+    // Trading Partners
+    // ========================================================================
+
+    pub async fn get_partner(&self, partner_id: Uuid) -> Result<Option<EdiTradingPartner>> {
+        let partner = sqlx::query_as::<_, EdiTradingPartner>(&format!(
+            "SELECT {} FROM edi_trading_partners WHERE id = $1",
+            PARTNER_COLUMNS
+        ))
+        .bind(partner_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(partner)
+    }
+
+    pub async fn get_partner_for_user(&self, user_id: Uuid) -> Result<Option<EdiTradingPartner>> {
+        let partner = sqlx::query_as::<_, EdiTradingPartner>(&format!(
+            "SELECT {} FROM edi_trading_partners WHERE user_id = $1",
+            PARTNER_COLUMNS
+        ))
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(partner)
+    }
+
+    /// Create or replace the user's partner profile. Secrets not supplied keep their
+    /// stored value; the AS2 inbound token is returned only when generated.
+    pub async fn upsert_partner(&self, user_id: Uuid, request: UpsertEdiPartnerRequest) -> Result<EdiPartnerResponse> {
+        let existing = self.get_partner_for_user(user_id).await?;
+
+        let x12_version = request.x12_version.clone().unwrap_or_else(|| "004010".to_string());
+        if !X12_VERSIONS.contains(&x12_version.as_str()) {
+            return Err(AppError::BadRequest(format!("x12_version must be one of {:?}", X12_VERSIONS)));
+        }
+
+        let encrypt = |value: &Option<String>| -> Result<Option<String>> {
+            Ok(match value.as_deref().map(str::trim).filter(|v| !v.is_empty()) {
+                Some(v) => Some(self.encryption_service.encrypt(v)?),
+                None => None,
+            })
+        };
+        let keep = |new: Option<String>, old: Option<&String>| new.or_else(|| old.cloned());
+
+        let as2_username = keep(encrypt(&request.as2_username)?, existing.as_ref().and_then(|p| p.as2_username.as_ref()));
+        let as2_password = keep(encrypt(&request.as2_password)?, existing.as_ref().and_then(|p| p.as2_password.as_ref()));
+        let sftp_username = keep(encrypt(&request.sftp_username)?, existing.as_ref().and_then(|p| p.sftp_username.as_ref()));
+        let sftp_password = keep(encrypt(&request.sftp_password)?, existing.as_ref().and_then(|p| p.sftp_password.as_ref()));
+        let sftp_private_key = keep(encrypt(&request.sftp_private_key)?, existing.as_ref().and_then(|p| p.sftp_private_key.as_ref()));
+
+        let mut new_token = None;
+        let mut as2_inbound_token = existing.as_ref().and_then(|p| p.as2_inbound_token.clone());
+        if request.transport == EdiTransport::As2 && (as2_inbound_token.is_none() || request.rotate_inbound_token) {
+            let mut bytes = [0u8; 32];
+            rand::thread_rng().fill_bytes(&mut bytes);
+            let token = hex::encode(bytes);
+            as2_inbound_token = Some(self.encryption_service.encrypt(&token)?);
+            new_token = Some(token);
+        }
+
+        match request.transport {
+            EdiTransport::As2 => {
+                if request.as2_id.is_none() || request.as2_url.is_none() {
+                    return Err(AppError::BadRequest("AS2 partners require as2_id and as2_url".to_string()));
+                }
+            }
+            EdiTransport::Sftp => {
+                if request.sftp_host.is_none()
+                    || sftp_username.is_none()
+                    || (sftp_password.is_none() && sftp_private_key.is_none())
+                    || request.sftp_inbound_path.is_none()
+                    || request.sftp_outbound_path.is_none()
+                {
+                    return Err(AppError::BadRequest(
+                        "SFTP partners require sftp_host, sftp_username, sftp_password or sftp_private_key, sftp_inbound_path and sftp_outbound_path".to_string(),
+                    ));
+                }
+            }
+        }
+
+        let isa_qualifier = request.isa_qualifier.clone().unwrap_or_else(|| "ZZ".to_string());
+        let isa_id = request.isa_id.trim().to_string();
+        let gs_id = request.gs_id.clone().unwrap_or_else(|| isa_id.clone());
+
+        let sql = match existing {
+            Some(_) => format!(
+                r#"
+                UPDATE edi_trading_partners SET
+                    name = $2, isa_qualifier = $3, isa_id = $4, gs_id = $5, x12_version = $6, test_mode = $7,
+                    transport = $8, as2_id = $9, as2_url = $10, as2_username = $11, as2_password = $12,
+                    as2_inbound_token = $13, sftp_host = $14, sftp_port = $15, sftp_username = $16,
+                    sftp_password = $17, sftp_private_key = $18, sftp_host_key_fingerprint = $19,
+                    sftp_inbound_path = $20, sftp_outbound_path = $21, sftp_archive_path = $22,
+                    enabled = $23, updated_at = NOW()
+                WHERE user_id = $1
+                RETURNING {}
+                "#,
+                PARTNER_COLUMNS
+            ),
+            None => format!(
+                r#"
+                INSERT INTO edi_trading_partners (
+                    user_id, name, isa_qualifier, isa_id, gs_id, x12_version, test_mode,
+                    transport, as2_id, as2_url, as2_username, as2_password,
+                    as2_inbound_token, sftp_host, sftp_port, sftp_username,
+                    sftp_password, sftp_private_key, sftp_host_key_fingerprint,
+                    sftp_inbound_path, sftp_outbound_path, sftp_archive_path, enabled
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23)
+                RETURNING {}
+                "#,
+                PARTNER_COLUMNS
+            ),
+        };
+
+        let partner = sqlx::query_as::<_, EdiTradingPartner>(&sql)
+            .bind(user_id)
+            .bind(&request.name)
+            .bind(&isa_qualifier)
+            .bind(&isa_id)
+            .bind(&gs_id)
+            .bind(&x12_version)
+            .bind(request.test_mode.unwrap_or(true))
+            .bind(request.transport.as_str())
+            .bind(&request.as2_id)
+            .bind(&request.as2_url)
+            .bind(&as2_username)
+            .bind(&as2_password)
+            .bind(&as2_inbound_token)
+            .bind(&request.sftp_host)
+            .bind(request.sftp_port.unwrap_or(22))
+            .bind(&sftp_username)
+            .bind(&sftp_password)
+            .bind(&sftp_private_key)
+            .bind(&request.sftp_host_key_fingerprint)
+            .bind(&request.sftp_inbound_path)
+            .bind(&request.sftp_outbound_path)
+            .bind(&request.sftp_archive_path)
+            .bind(request.enabled.unwrap_or(true))
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::Database(ref db) if db.constraint() == Some("idx_edi_trading_partners_isa") => {
+                    AppError::Conflict
+                }
+                other => AppError::Database(other),
+            })?;
+
+        Ok(self.partner_response(&partner, new_token))
+    }
+
+    pub async fn delete_partner(&self, user_id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM edi_trading_partners WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub fn partner_response(&self, partner: &EdiTradingPartner, as2_inbound_token: Option<String>) -> EdiPartnerResponse {
+        EdiPartnerResponse {
+            id: partner.id,
+            name: partner.name.clone(),
+            isa_qualifier: partner.isa_qualifier.clone(),
+            isa_id: partner.isa_id.clone(),
+            gs_id: partner.gs_id.clone(),
+            x12_version: partner.x12_version.clone(),
+            test_mode: partner.test_mode,
+            transport: partner.transport.clone(),
+            enabled: partner.enabled,
+            as2_id: partner.as2_id.clone(),
+            as2_url: partner.as2_url.clone(),
+            as2_inbound_path: format!("/api/edi/as2/{}", partner.id),
+            as2_inbound_token,
+            sftp_host: partner.sftp_host.clone(),
+            sftp_port: partner.sftp_port,
+            sftp_inbound_path: partner.sftp_inbound_path.clone(),
+            sftp_outbound_path: partner.sftp_outbound_path.clone(),
+            sftp_archive_path: partner.sftp_archive_path.clone(),
+            has_sftp_credentials: partner.sftp_password.is_some() || partner.sftp_private_key.is_some(),
+            last_polled_at: partner.last_polled_at,
+            atlas_isa_qualifier: self.identity.isa_qualifier.clone(),
+            atlas_isa_id: self.identity.isa_id.clone(),
+            atlas_gs_id: self.identity.gs_id.clone(),
+            atlas_as2_id: self.identity.as2_id.clone(),
+            created_at: partner.created_at,
+            updated_at: partner.updated_at,
+        }
+    }
+
+    /// Constant-time check of the token an AS2 partner presents
+    pub fn verify_inbound_token(&self, partner: &EdiTradingPartner, token: &str) -> Result<bool> {
+        use subtle::ConstantTimeEq;
+
+        let Some(encrypted) = &partner.as2_inbound_token else {
+            return Ok(false);
+        };
+        let expected = self.encryption_service.decrypt(encrypted)?;
+        Ok(expected.as_bytes().ct_eq(token.as_bytes()).into())
+    }
+
+    fn transport_client(&self, partner: &EdiTradingPartner) -> Result<EdiTransportClient> {
+        let decrypt = |value: &Option<String>| -> Result<Option<String>> {
+            Ok(self.encryption_service.decrypt_optional(value.as_ref())?)
+        };
+
+        match EdiTransport::parse(&partner.transport) {
+            Some(EdiTransport::As2) => Ok(EdiTransportClient::As2(As2Client::new(As2Config {
+                url: partner.as2_url.clone().unwrap_or_default(),
+                partner_as2_id: partner.as2_id.clone().unwrap_or_default(),
+                atlas_as2_id: self.identity.as2_id.clone(),
+                username: decrypt(&partner.as2_username)?,
+                password: decrypt(&partner.as2_password)?,
+            }))),
+            Some(EdiTransport::Sftp) => Ok(EdiTransportClient::Sftp(self.sftp_client(partner)?)),
+            None => Err(AppError::Internal(anyhow::anyhow!("Unknown EDI transport '{}'", partner.transport))),
+        }
+    }
+
+    fn sftp_client(&self, partner: &EdiTradingPartner) -> Result<SftpClient> {
+        let config = SftpConfig {
+            host: partner.sftp_host.clone().unwrap_or_default(),
+            port: partner.sftp_port.unwrap_or(22) as u16,
+            username: self.encryption_service.decrypt_optional(partner.sftp_username.as_ref())?.unwrap_or_default(),
+            password: self.encryption_service.decrypt_optional(partner.sftp_password.as_ref())?,
+            private_key: self.encryption_service.decrypt_optional(partner.sftp_private_key.as_ref())?,
+            host_key_fingerprint: partner.sftp_host_key_fingerprint.clone(),
+            inbound_path: partner.sftp_inbound_path.clone().unwrap_or_default(),
+            outbound_path: partner.sftp_outbound_path.clone().unwrap_or_default(),
+            archive_path: partner.sftp_archive_path.clone(),
+            file_pattern: "*".to_string(),
+        };
+
+        SftpClient::new(config).map_err(|e| AppError::BadRequest(e.to_string()))
+    }
+
+    /// Next ISA13/GS06 control number for the partner (wraps after 999999999)
+    async fn next_control_number(&self, partner_id: Uuid) -> Result<u32> {
+        let number: i32 = sqlx::query_scalar(
+            r#"
+            UPDATE edi_trading_partners
+            SET last_control_number = CASE WHEN last_control_number >= 999999999 THEN 1 ELSE last_control_number + 1 END
+            WHERE id = $1
+            RETURNING last_control_number
+            "#
+        )
+        .bind(partner_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(number as u32)
+    }
+
+    // ========================================================================
+    // Outbound: 850 Purchase Orders
+    // ========================================================================
+
+    /// Emit an 850 to the seller's trading partner. Returns `None` when the seller has no
+    /// enabled partner profile, and the existing document when the order was already sent
+    /// or another call is sending it. The attempt is claimed by inserting a `pending`
+    /// document (at most one pending or sent 850 per transaction), so concurrent calls
+    /// send the order once; the transport runs outside any transaction.
+    pub async fn send_purchase_order(&self, transaction_id: Uuid) -> Result<Option<EdiDocument>> {
+        let source = sqlx::query_as::<_, PurchaseOrderSource>(
+            r#"
+            SELECT
+                t.transaction_date, t.seller_id, t.quantity, t.unit_price,
+                i.id AS inventory_id, i.batch_number,
+                p.ndc_code, p.brand_name, p.generic_name, p.strength,
+                b.company_name AS buyer_name, s.company_name AS seller_name
+            FROM transactions t
+            JOIN inquiries q ON q.id = t.inquiry_id
+            JOIN inventory i ON i.id = q.inventory_id
+            JOIN pharmaceuticals p ON p.id = i.pharmaceutical_id
+            JOIN users b ON b.id = t.buyer_id
+            JOIN users s ON s.id = t.seller_id
+            WHERE t.id = $1
+            "#
+        )
+        .bind(transaction_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Transaction {} not found", transaction_id)))?;
+
+        let Some(partner) = self.get_partner_for_user(source.seller_id).await? else {
+            return Ok(None);
+        };
+        if !partner.enabled {
+            return Ok(None);
+        }
+
+        // A claim left behind by an instance that stopped mid-send no longer blocks retries
+        sqlx::query(
+            r#"
+            UPDATE edi_documents
+            SET status = 'failed', error_message = 'Send was interrupted'
+            WHERE transaction_id = $1 AND direction = 'outbound' AND transaction_set = '850'
+              AND status = 'pending' AND created_at < NOW() - make_interval(mins => $2)
+            "#
+        )
+        .bind(transaction_id)
+        .bind(PENDING_SEND_TIMEOUT_MINUTES as i32)
+        .execute(&self.pool)
+        .await?;
+
+        let previous = sqlx::query_as::<_, EdiDocument>(&format!(
+            r#"
+            SELECT {} FROM edi_documents d
+            WHERE d.transaction_id = $1 AND d.direction = 'outbound' AND d.transaction_set = '850'
+            ORDER BY d.created_at DESC
+            "#,
+            DOCUMENT_COLUMNS
+        ))
+        .bind(transaction_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        if let Some(claimed) = previous.iter().find(|d| d.status == "sent" || d.status == "pending") {
+            return Ok(Some(claimed.clone()));
+        }
+
+        // A retry keeps the PO number of the failed attempt
+        let po_number = match previous.first().and_then(|d| d.po_number.clone()) {
+            Some(po_number) => po_number,
+            None => {
+                let sequence: i64 = sqlx::query_scalar("SELECT nextval('edi_po_number_seq')")
+                    .fetch_one(&self.pool)
+                    .await?;
+                format!("ATL{:010}", sequence)
+            }
+        };
+
+        let (product_id_qualifier, product_id) = match source.ndc_code.as_deref().and_then(ndc_to_11_digit) {
+            Some(ndc) => ("N4".to_string(), ndc),
+            None => ("VP".to_string(), source.inventory_id.to_string()),
+        };
+        let description = match &source.strength {
+            Some(strength) => format!("{} ({}) {}", source.brand_name, source.generic_name, strength),
+            None => format!("{} ({})", source.brand_name, source.generic_name),
+        };
+
+        let order = EdiPurchaseOrder {
+            po_number: po_number.clone(),
+            po_date: source.transaction_date.date_naive(),
+            buyer_name: source.buyer_name,
+            seller_name: source.seller_name,
+            currency: "USD".to_string(),
+            lines: vec![EdiOrderLine {
+                line_number: 1,
+                quantity: source.quantity,
+                unit_price: source.unit_price,
+                product_id_qualifier,
+                product_id,
+                lot_number: Some(source.batch_number),
+                description: Some(description),
+            }],
+        };
+
+        let control_number = self.next_control_number(partner.id).await?;
+        let envelope = OutboundEnvelope {
+            sender_qualifier: self.identity.isa_qualifier.clone(),
+            sender_id: self.identity.isa_id.clone(),
+            receiver_qualifier: partner.isa_qualifier.clone(),
+            receiver_id: partner.isa_id.clone(),
+            gs_sender_id: self.identity.gs_id.clone(),
+            gs_receiver_id: partner.gs_id.clone(),
+            control_number,
+            version: partner.x12_version.clone(),
+            test: partner.test_mode,
+            timestamp: Utc::now(),
+        };
+        let set = EdiTransactionSet::PurchaseOrder;
+        let raw_x12 = x12::write_interchange(&envelope, set.code(), set.functional_id(), &build_850(&order));
+
+        let transport = self.transport_client(&partner)?;
+
+        // Claim the send; a concurrent call that claimed it first returns its document
+        let claimed = sqlx::query_as::<_, EdiDocument>(&format!(
+            r#"
+            INSERT INTO edi_documents AS d (
+                partner_id, transaction_id, direction, transaction_set, interchange_control_number,
+                transaction_control_number, po_number, transport, status, raw_x12, translated
+            )
+            VALUES ($1, $2, 'outbound', '850', $3, $3, $4, $5, 'pending', $6, $7)
+            ON CONFLICT DO NOTHING
+            RETURNING {}
+            "#,
+            DOCUMENT_COLUMNS
+        ))
+        .bind(partner.id)
+        .bind(transaction_id)
+        .bind(envelope.control_number_str())
+        .bind(&po_number)
+        .bind(&partner.transport)
+        .bind(&raw_x12)
+        .bind(serde_json::to_value(&order)?)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let Some(claimed) = claimed else {
+            let existing = sqlx::query_as::<_, EdiDocument>(&format!(
+                r#"
+                SELECT {} FROM edi_documents d
+                WHERE d.transaction_id = $1 AND d.direction = 'outbound' AND d.transaction_set = '850'
+                  AND d.status IN ('pending', 'sent')
+                "#,
+                DOCUMENT_COLUMNS
+            ))
+            .bind(transaction_id)
+            .fetch_optional(&self.pool)
+            .await?;
+            return Ok(existing);
+        };
+
+        let file_name = format!("850_{}_{}.x12", po_number, envelope.control_number_str());
+        let (status, notes, error_message) = match transport.send(&file_name, &raw_x12).await {
+            Ok(receipt) => ("sent", Some(format!("Delivered as {}", receipt.reference)), None),
+            Err(e) => {
+                tracing::warn!("850 for transaction {} to partner {} failed: {}", transaction_id, partner.id, e);
+                ("failed", None, Some(e.to_string()))
+            }
+        };
+
+        let document = sqlx::query_as::<_, EdiDocument>(&format!(
+            r#"
+            UPDATE edi_documents AS d
+            SET status = $2, notes = $3, error_message = $4
+            WHERE d.id = $1
+            RETURNING {}
+            "#,
+            DOCUMENT_COLUMNS
+        ))
+        .bind(claimed.id)
+        .bind(status)
+        .bind(notes)
+        .bind(error_message)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(Some(document))
+    }
+
+    // ========================================================================
+    // Inbound: 855 / 856 / 810
+    // ========================================================================
+
+    /// Parse an interchange from a partner and apply each transaction set in it.
+    /// Transaction sets already received (same ISA13 and ST02) are skipped, unless a
+    /// receipt stopped before applying them; those are applied again.
+    pub async fn receive_interchange(
+        &self,
+        partner: &EdiTradingPartner,
+        raw: &str,
+        transport: &str,
+    ) -> Result<Vec<EdiDocument>> {
+        let interchange = x12::parse(raw).map_err(|e| AppError::BadRequest(format!("Invalid X12: {}", e)))?;
+
+        if !interchange.sender_id.eq_ignore_ascii_case(partner.isa_id.trim()) {
+            return Err(AppError::BadRequest(format!(
+                "ISA06 sender '{}' does not match the partner's ISA id '{}'",
+                interchange.sender_id, partner.isa_id
+            )));
+        }
+        if !interchange.receiver_id.eq_ignore_ascii_case(&self.identity.isa_id) {
+            return Err(AppError::BadRequest(format!(
+                "ISA08 receiver '{}' is not Atlas ({})",
+                interchange.receiver_id, self.identity.isa_id
+            )));
+        }
+
+        let mut documents = Vec::new();
+        for group in &interchange.groups {
+            for set in &group.transaction_sets {
+                if !matches!(set.id.as_str(), "855" | "856" | "810") {
+                    tracing::warn!(
+                        "Ignoring unsupported transaction set {} from EDI partner {}",
+                        set.id, partner.id
+                    );
+                    continue;
+                }
+
+                let translated = translate_inbound(set);
+                let (status, translated_json, error_message, po_number) = match &translated {
+                    Ok(document) => (
+                        "unmatched",
+                        serde_json::to_value(document)?,
+                        None,
+                        document.po_number().map(|po| po.chars().take(22).collect::<String>()),
+                    ),
+                    Err(e) => ("rejected", serde_json::json!({}), Some(e.to_string()), None),
+                };
+
+                let inserted = sqlx::query_as::<_, EdiDocument>(&format!(
+                    r#"
+                    INSERT INTO edi_documents AS d (
+                        partner_id, direction, transaction_set, interchange_control_number,
+                        transaction_control_number, po_number, transport, status, raw_x12, translated, error_message
+                    )
+                    VALUES ($1, 'inbound', $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                    ON CONFLICT (partner_id, interchange_control_number, transaction_control_number)
+                        WHERE direction = 'inbound'
+                        DO NOTHING
+                    RETURNING {}
+                    "#,
+                    DOCUMENT_COLUMNS
+                ))
+                .bind(partner.id)
+                .bind(&set.id)
+                .bind(&interchange.control_number)
+                .bind(&set.control_number)
+                .bind(&po_number)
+                .bind(transport)
+                .bind(status)
+                .bind(raw)
+                .bind(translated_json)
+                .bind(error_message)
+                .fetch_optional(&self.pool)
+                .await?;
+
+                let document = match inserted {
+                    Some(document) => document,
+                    None => {
+                        let existing = sqlx::query_as::<_, EdiDocument>(&format!(
+                            r#"
+                            SELECT {} FROM edi_documents d
+                            WHERE d.partner_id = $1 AND d.direction = 'inbound'
+                              AND d.interchange_control_number = $2 AND d.transaction_control_number = $3
+                            "#,
+                            DOCUMENT_COLUMNS
+                        ))
+                        .bind(partner.id)
+                        .bind(&interchange.control_number)
+                        .bind(&set.control_number)
+                        .fetch_one(&self.pool)
+                        .await?;
+
+                        if existing.status != "unmatched" {
+                            tracing::info!(
+                                "Skipping duplicate {} (ISA13 {}, ST02 {}) from EDI partner {}",
+                                set.id, interchange.control_number, set.control_number, partner.id
+                            );
+                            continue;
+                        }
+                        tracing::info!(
+                            "Applying unmatched {} (ISA13 {}, ST02 {}) from EDI partner {} again",
+                            set.id, interchange.control_number, set.control_number, partner.id
+                        );
+                        existing
+                    }
+                };
+
+                match translated {
+                    Ok(translated) => documents.push(self.apply_inbound(partner, document, &translated).await?),
+                    Err(_) => documents.push(document),
+                }
+            }
+        }
+
+        Ok(documents)
+    }
+
+    /// Match the document to the transaction of its purchase order and apply it
+    async fn apply_inbound(
+        &self,
+        partner: &EdiTradingPartner,
+        document: EdiDocument,
+        translated: &EdiInboundDocument,
+    ) -> Result<EdiDocument> {
+        let transaction_id: Option<Uuid> = match translated.po_number() {
+            Some(po_number) => sqlx::query_scalar(
+                r#"
+                SELECT transaction_id FROM edi_documents
+                WHERE partner_id = $1 AND direction = 'outbound' AND transaction_set = '850'
+                  AND po_number = $2 AND transaction_id IS NOT NULL
+                ORDER BY created_at DESC
+                LIMIT 1
+                "#
+            )
+            .bind(partner.id)
+            .bind(po_number)
+            .fetch_optional(&self.pool)
+            .await?,
+            None => None,
+        };
+
+        let Some(transaction_id) = transaction_id else {
+            return Ok(document);
+        };
+
+        let totals = sqlx::query_as::<_, TransactionTotals>(
+            "SELECT status, quantity, total_price FROM transactions WHERE id = $1"
+        )
+        .bind(transaction_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let notes = match translated {
+            EdiInboundDocument::Acknowledgment(ack) => match ack.status {
+                EdiAckStatus::Accepted => "Purchase order accepted".to_string(),
+                EdiAckStatus::AcceptedWithChanges => {
+                    let changes: Vec<String> = ack.lines
+                        .iter()
+                        .filter(|l| l.status_code != "IA")
+                        .map(|l| match l.quantity {
+                            Some(quantity) => format!("{} ({} units)", l.status_code, quantity),
+                            None => l.status_code.clone(),
+                        })
+                        .collect();
+                    format!("Purchase order accepted with changes: {}", changes.join(", "))
+                }
+                EdiAckStatus::Rejected if totals.status == "pending" => {
+                    self.marketplace_service()?
+                        .cancel_transaction(transaction_id, partner.user_id)
+                        .await?;
+                    "Purchase order rejected; transaction cancelled".to_string()
+                }
+                EdiAckStatus::Rejected => format!("Purchase order rejected; transaction already {}", totals.status),
+            },
+            EdiInboundDocument::ShipNotice(asn) => {
+                let shipped: i32 = asn.lines.iter().filter_map(|l| l.quantity).sum();
+                if shipped != totals.quantity {
+                    format!("Shipment {}: {} units shipped, {} ordered", asn.shipment_id, shipped, totals.quantity)
+                } else {
+                    format!("Shipment {}: {} units shipped", asn.shipment_id, shipped)
+                }
+            }
+            EdiInboundDocument::Invoice(invoice) => {
+                if invoice.total_amount != totals.total_price {
+                    format!(
+                        "Invoice {} total {} differs from the transaction total {}",
+                        invoice.invoice_number, invoice.total_amount, totals.total_price
+                    )
+                } else {
+                    format!("Invoice {} matches the transaction total", invoice.invoice_number)
+                }
+            }
+        };
+
+        let document = sqlx::query_as::<_, EdiDocument>(&format!(
+            r#"
+            UPDATE edi_documents AS d
+            SET transaction_id = $2, status = 'applied', notes = $3
+            WHERE d.id = $1
+            RETURNING {}
+            "#,
+            DOCUMENT_COLUMNS
+        ))
+        .bind(document.id)
+        .bind(transaction_id)
+        .bind(notes)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(document)
+    }
+
+    fn marketplace_service(&self) -> Result<crate::services::MarketplaceService> {
+        use crate::repositories::{InventoryRepository, MarketplaceRepository, PharmaceuticalRepository, UserRepository};
+
+        Ok(crate::services::MarketplaceService::new(
+            MarketplaceRepository::new(self.pool.clone()),
+            InventoryRepository::new(self.pool.clone()),
+            UserRepository::new(self.pool.clone(), &self.encryption_key)?,
+            PharmaceuticalRepository::new(self.pool.clone()),
+            crate::services::InventoryService::new(
+                InventoryRepository::new(self.pool.clone()),
+                PharmaceuticalRepository::new(self.pool.clone()),
+            ),
+        ))
+    }
+
+    /// Download and apply every file in an SFTP partner's inbound directory.
+    /// Processed files are archived; files that fail are left for the next poll.
+    pub async fn poll_sftp_partner(&self, partner: &EdiTradingPartner) -> Result<usize> {
+        let client = self.sftp_client(partner)?;
+        let sftp_error = |e: crate::services::erp::SftpError| AppError::BadRequest(format!("SFTP error: {}", e));
+
+        let mut applied = 0;
+        for file in client.list_inbound().await.map_err(sftp_error)? {
+            let contents = match client.download(&file.path).await {
+                Ok(contents) => contents,
+                Err(e) => {
+                    tracing::warn!("Failed to download {} from EDI partner {}: {}", file.path, partner.id, e);
+                    continue;
+                }
+            };
+
+            match self.receive_interchange(partner, &String::from_utf8_lossy(&contents), "sftp").await {
+                Ok(documents) => {
+                    applied += documents.len();
+                    client.archive(&file.path, &file.name).await.map_err(sftp_error)?;
+                }
+                Err(e) => tracing::warn!("EDI file {} from partner {} not processed: {}", file.name, partner.id, e),
+            }
+        }
+
+        sqlx::query("UPDATE edi_trading_partners SET last_polled_at = NOW() WHERE id = $1")
+            .bind(partner.id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(applied)
+    }
+
+    // ========================================================================
+    // Queries
+    // ========================================================================
+
+    /// Documents of the user's partner profile and of transactions the user is party to
+    pub async fn list_documents(&self, user_id: Uuid, query: &EdiDocumentQuery) -> Result<Vec<EdiDocument>> {
+        let documents = sqlx::query_as::<_, EdiDocument>(&format!(
+            r#"
+            SELECT {} FROM edi_documents d
+            JOIN edi_trading_partners p ON p.id = d.partner_id
+            LEFT JOIN transactions t ON t.id = d.transaction_id
+            WHERE (p.user_id = $1 OR t.buyer_id = $1 OR t.seller_id = $1)
+              AND ($2::UUID IS NULL OR d.transaction_id = $2)
+            ORDER BY d.created_at DESC
+            LIMIT $3 OFFSET $4
+            "#,
+            DOCUMENT_COLUMNS
+        ))
+        .bind(user_id)
+        .bind(query.transaction_id)
+        .bind(query.limit.unwrap_or(50).clamp(1, 200))
+        .bind(query.offset.unwrap_or(0).max(0))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(documents)
+    }
+
+    /// A document the user can see (see `list_documents`)
+    pub async fn get_document(&self, user_id: Uuid, document_id: Uuid) -> Result<EdiDocument> {
+        sqlx::query_as::<_, EdiDocument>(&format!(
+            r#"
+            SELECT {} FROM edi_documents d
+            JOIN edi_trading_partners p ON p.id = d.partner_id
+            LEFT JOIN transactions t ON t.id = d.transaction_id
+            WHERE d.id = $2 AND (p.user_id = $1 OR t.buyer_id = $1 OR t.seller_id = $1)
+            "#,
+            DOCUMENT_COLUMNS
+        ))
+        .bind(user_id)
+        .bind(document_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("EDI document not found".to_string()))
+    }
+
+    async fn sftp_partners(&self) -> Result<Vec<EdiTradingPartner>> {
+        let partners = sqlx::query_as::<_, EdiTradingPartner>(&format!(
+            "SELECT {} FROM edi_trading_partners WHERE transport = 'sftp' AND enabled = true ORDER BY last_polled_at NULLS FIRST",
+            PARTNER_COLUMNS
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(partners)
+    }
+}
+
+// ============================================================================
+// Inbound Polling Scheduler
+// ============================================================================
+
+/// Polls the inbound directory of every SFTP trading partner
+pub struct EdiPollScheduler {
+    pool: PgPool,
+    poll_minutes: u64,
+}
+
+impl EdiPollScheduler {
+    pub fn new(pool: PgPool) -> Self {
+        let poll_minutes = std::env::var("EDI_POLL_MINUTES")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .filter(|m| *m > 0)
+            .unwrap_or(5);
+
+        Self { pool, poll_minutes }
+    }
+
+    /// Run the scheduler loop
+    pub async fn run(&self) {
+        let mut ticker = tokio::time::interval(std::time::Duration::from_secs(self.poll_minutes * 60));
+
+        tracing::info!("EDI poll scheduler started - polling SFTP partners every {} minutes", self.poll_minutes);
+
+        loop {
+            let deadline = ticker.tick().await;
+            crate::middleware::metrics::record_scheduler_lag("edi_poll", deadline);
+            if crate::services::MaintenanceService::pauses("edi_poll")
+                || !crate::services::SchedulerLeader::leads("edi_poll").await
+            {
+                continue;
+            }
+            self.poll_partners().await;
+        }
+    }
+
+    pub async fn poll_partners(&self) {
+        let service = match EdiService::new(self.pool.clone()) {
+            Ok(service) => service,
+            Err(e) => {
+                tracing::error!("EDI poll scheduler cannot start: {}", e);
+                return;
+            }
+        };
+
+        let partners = match service.sftp_partners().await {
+            Ok(partners) => partners,
+            Err(e) => {
+                tracing::error!("Failed to load SFTP trading partners: {}", e);
+                return;
+            }
+        };
+
+        for partner in partners {
+            match service.poll_sftp_partner(&partner).await {
+                Ok(0) => {}
+                Ok(applied) => tracing::info!("Received {} EDI documents from partner {}", applied, partner.id),
+                Err(e) => tracing::warn!("EDI poll of partner {} failed: {}", partner.id, e),
+            }
+        }
+    }
+}
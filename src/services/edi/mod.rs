@@ -0,0 +1,12 @@
+// EDI Module
+// ANSI X12 exchange with marketplace trading partners: envelope codec, translation of
+// 850/855/856/810 to and from the internal models, AS2/SFTP transport, and the service
+
+pub mod x12;
+pub mod translate;
+pub mod transport;
+pub mod edi_service;
+
+pub use x12::{Interchange, OutboundEnvelope, Segment, TransactionSet, X12Error};
+pub use transport::{As2Client, As2Config, EdiTransportClient, EdiTransportError};
+pub use edi_service::{EdiIdentity, EdiPollScheduler, EdiService};
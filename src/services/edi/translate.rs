@@ -0,0 +1,371 @@
+// Translation between X12 transaction sets and the internal EDI models
+// 850 is built from a purchase order; 855, 856 and 810 are read into their models
+
+use chrono::NaiveDate;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use std::str::FromStr;
+
+use super::x12::{Result, Segment, TransactionSet};
+use crate::models::edi::{
+    EdiAckLine, EdiAckStatus, EdiAcknowledgment, EdiInboundDocument, EdiInvoice, EdiInvoiceLine,
+    EdiPurchaseOrder, EdiShipLine, EdiShipNotice,
+};
+
+// ============================================================================
+// Outbound
+// ============================================================================
+
+/// Body segments (between ST and SE) of an 850 purchase order
+pub fn build_850(order: &EdiPurchaseOrder) -> Vec<Segment> {
+    let po_date = order.po_date.format("%Y%m%d").to_string();
+    let mut segments = vec![
+        // 00 = original, SA = stand-alone order
+        Segment::new("BEG", &["00", "SA", &order.po_number, "", &po_date]),
+        Segment::new("CUR", &["BY", &order.currency]),
+        Segment::new("N1", &["BY", &truncate(&order.buyer_name, 60)]),
+        Segment::new("N1", &["SE", &truncate(&order.seller_name, 60)]),
+    ];
+
+    for line in &order.lines {
+        let mut po1 = vec![
+            line.line_number.to_string(),
+            line.quantity.to_string(),
+            "EA".to_string(),
+            line.unit_price.normalize().to_string(),
+            String::new(),
+            line.product_id_qualifier.clone(),
+            truncate(&line.product_id, 48),
+        ];
+        if let Some(lot) = &line.lot_number {
+            po1.push("LT".to_string());
+            po1.push(truncate(lot, 48));
+        }
+        segments.push(Segment {
+            id: "PO1".to_string(),
+            elements: po1,
+        });
+
+        if let Some(description) = &line.description {
+            // F = free-form description
+            segments.push(Segment::new("PID", &["F", "", "", "", &truncate(description, 80)]));
+        }
+    }
+
+    let hash_total: i64 = order.lines.iter().map(|l| l.quantity as i64).sum();
+    segments.push(Segment::new("CTT", &[&order.lines.len().to_string(), &hash_total.to_string()]));
+    segments
+}
+
+// ============================================================================
+// Inbound
+// ============================================================================
+
+/// Translate a received transaction set
+pub fn translate_inbound(set: &TransactionSet) -> Result<EdiInboundDocument> {
+    match set.id.as_str() {
+        "855" => parse_855(set).map(EdiInboundDocument::Acknowledgment),
+        "856" => parse_856(set).map(EdiInboundDocument::ShipNotice),
+        "810" => parse_810(set).map(EdiInboundDocument::Invoice),
+        other => Err(set.invalid(format!("transaction set {} is not accepted from partners", other))),
+    }
+}
+
+pub fn parse_855(set: &TransactionSet) -> Result<EdiAcknowledgment> {
+    let bak = set.find("BAK").ok_or_else(|| set.invalid("missing BAK segment"))?;
+    let status_code = bak.element(2).trim().to_string();
+    let status = match status_code.as_str() {
+        "AD" | "AK" => EdiAckStatus::Accepted,
+        "AC" | "AE" => EdiAckStatus::AcceptedWithChanges,
+        "RD" | "RJ" => EdiAckStatus::Rejected,
+        other => return Err(set.invalid(format!("unknown BAK02 acknowledgment type '{}'", other))),
+    };
+    let po_number = bak.value(3).ok_or_else(|| set.invalid("BAK03 purchase order number is empty"))?;
+
+    let mut lines = Vec::new();
+    let mut current_line: Option<(Option<String>, Option<String>)> = None;
+    for segment in &set.segments {
+        match segment.id.as_str() {
+            "PO1" => current_line = Some((segment.value(1).map(str::to_string), product_id(segment, 6))),
+            "ACK" => {
+                let (line_number, product_id) = current_line.clone().unwrap_or((None, None));
+                lines.push(EdiAckLine {
+                    line_number,
+                    status_code: segment.element(1).trim().to_string(),
+                    quantity: segment.value(2).and_then(parse_quantity),
+                    product_id,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    Ok(EdiAcknowledgment {
+        po_number: po_number.to_string(),
+        status,
+        status_code,
+        ack_date: bak.value(4).and_then(parse_date),
+        lines,
+    })
+}
+
+pub fn parse_856(set: &TransactionSet) -> Result<EdiShipNotice> {
+    let bsn = set.find("BSN").ok_or_else(|| set.invalid("missing BSN segment"))?;
+    let shipment_id = bsn.value(2).ok_or_else(|| set.invalid("BSN02 shipment identifier is empty"))?;
+
+    let mut notice = EdiShipNotice {
+        shipment_id: shipment_id.to_string(),
+        ship_date: bsn.value(3).and_then(parse_date),
+        po_numbers: Vec::new(),
+        carrier: None,
+        tracking_number: None,
+        lines: Vec::new(),
+    };
+
+    // The HL hierarchy is flattened: items start at LIN and collect SN1 and lot dates
+    let mut item: Option<EdiShipLine> = None;
+    for segment in &set.segments {
+        match segment.id.as_str() {
+            "HL" => {
+                if let Some(done) = item.take() {
+                    notice.lines.push(done);
+                }
+            }
+            "TD5" => {
+                if notice.carrier.is_none() {
+                    notice.carrier = segment.value(5).or(segment.value(3)).map(str::to_string);
+                }
+            }
+            // CN = carrier's reference (PRO/tracking), BM = bill of lading
+            "REF" if matches!(segment.element(1), "CN" | "BM") && notice.tracking_number.is_none() => {
+                notice.tracking_number = segment.value(2).map(str::to_string);
+            }
+            "PRF" => {
+                if let Some(po) = segment.value(1) {
+                    if !notice.po_numbers.iter().any(|p| p == po) {
+                        notice.po_numbers.push(po.to_string());
+                    }
+                }
+            }
+            "LIN" => {
+                if let Some(done) = item.take() {
+                    notice.lines.push(done);
+                }
+                item = Some(EdiShipLine {
+                    product_id: product_id(segment, 2),
+                    lot_number: qualified_value(segment, 2, "LT"),
+                    ..Default::default()
+                });
+            }
+            "SN1" => {
+                if let Some(current) = item.as_mut() {
+                    current.quantity = segment.value(2).and_then(parse_quantity);
+                }
+            }
+            // 036 = expiration
+            "DTM" if segment.element(1) == "036" => {
+                if let Some(current) = item.as_mut() {
+                    current.expiry_date = segment.value(2).and_then(parse_date);
+                }
+            }
+            // 011 = shipped
+            "DTM" if segment.element(1) == "011" && notice.ship_date.is_none() => {
+                notice.ship_date = segment.value(2).and_then(parse_date);
+            }
+            _ => {}
+        }
+    }
+    if let Some(done) = item.take() {
+        notice.lines.push(done);
+    }
+
+    if notice.po_numbers.is_empty() {
+        return Err(set.invalid("no PRF purchase order reference"));
+    }
+    Ok(notice)
+}
+
+pub fn parse_810(set: &TransactionSet) -> Result<EdiInvoice> {
+    let big = set.find("BIG").ok_or_else(|| set.invalid("missing BIG segment"))?;
+    let invoice_number = big.value(2).ok_or_else(|| set.invalid("BIG02 invoice number is empty"))?;
+    let po_number = big.value(4).ok_or_else(|| set.invalid("BIG04 purchase order number is empty"))?;
+
+    let mut lines = Vec::new();
+    for segment in set.segments.iter().filter(|s| s.id == "IT1") {
+        let quantity = segment.value(2).and_then(parse_quantity)
+            .ok_or_else(|| set.invalid("IT102 quantity is not a whole number"))?;
+        let unit_price = segment.value(4).and_then(|p| Decimal::from_str(p).ok())
+            .ok_or_else(|| set.invalid("IT104 unit price is not a number"))?;
+        lines.push(EdiInvoiceLine {
+            line_number: segment.value(1).map(str::to_string),
+            quantity,
+            unit_price,
+            product_id: product_id(segment, 6),
+        });
+    }
+
+    // TDS01 carries the total with two implied decimals
+    let total_amount = match set.find("TDS").and_then(|tds| tds.value(1)) {
+        Some(cents) => cents.parse::<i64>().map(|c| Decimal::new(c, 2))
+            .map_err(|_| set.invalid("TDS01 total is not a number"))?,
+        None => lines.iter().map(|l| l.unit_price * Decimal::from(l.quantity)).sum(),
+    };
+
+    Ok(EdiInvoice {
+        invoice_number: invoice_number.to_string(),
+        invoice_date: big.value(1).and_then(parse_date),
+        po_number: po_number.to_string(),
+        total_amount,
+        lines,
+    })
+}
+
+/// First product ID in qualifier/value pairs starting at `first_qualifier`, preferring an NDC
+fn product_id(segment: &Segment, first_qualifier: usize) -> Option<String> {
+    qualified_value(segment, first_qualifier, "N4")
+        .or_else(|| qualified_value(segment, first_qualifier, "ND"))
+        .or_else(|| {
+            ["VP", "VN", "BP", "IN", "UP"]
+                .iter()
+                .find_map(|q| qualified_value(segment, first_qualifier, q))
+        })
+}
+
+/// Value following `qualifier` in qualifier/value pairs starting at `first_qualifier`
+fn qualified_value(segment: &Segment, first_qualifier: usize, qualifier: &str) -> Option<String> {
+    (first_qualifier..=segment.elements.len())
+        .step_by(2)
+        .find(|&i| segment.element(i).trim() == qualifier)
+        .and_then(|i| segment.value(i + 1))
+        .map(str::to_string)
+}
+
+/// Quantities are whole units; "10.0" is accepted, "10.5" is not
+fn parse_quantity(value: &str) -> Option<i32> {
+    let decimal = Decimal::from_str(value.trim()).ok()?;
+    if decimal.fract().is_zero() {
+        decimal.to_i32()
+    } else {
+        None
+    }
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.trim(), "%Y%m%d").ok()
+}
+
+fn truncate(value: &str, max: usize) -> String {
+    value.chars().take(max).collect()
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::edi::EdiOrderLine;
+
+    fn set(id: &str, segments: &[&str]) -> TransactionSet {
+        TransactionSet {
+            id: id.to_string(),
+            control_number: "0001".to_string(),
+            segments: segments
+                .iter()
+                .map(|raw| {
+                    let mut parts = raw.split('*');
+                    Segment {
+                        id: parts.next().unwrap().to_string(),
+                        elements: parts.map(str::to_string).collect(),
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_build_850() {
+        let order = EdiPurchaseOrder {
+            po_number: "ATL0000000007".to_string(),
+            po_date: NaiveDate::from_ymd_opt(2026, 3, 1).unwrap(),
+            buyer_name: "Northside Pharmacy".to_string(),
+            seller_name: "Acme Wholesale".to_string(),
+            currency: "USD".to_string(),
+            lines: vec![EdiOrderLine {
+                line_number: 1,
+                quantity: 24,
+                unit_price: Decimal::new(1250, 2),
+                product_id_qualifier: "N4".to_string(),
+                product_id: "00002322730".to_string(),
+                lot_number: Some("B123".to_string()),
+                description: Some("Humalog 100 U/mL".to_string()),
+            }],
+        };
+
+        let segments = build_850(&order);
+        assert_eq!(segments[0], Segment::new("BEG", &["00", "SA", "ATL0000000007", "", "20260301"]));
+        assert_eq!(
+            segments.iter().find(|s| s.id == "PO1").unwrap().elements,
+            vec!["1", "24", "EA", "12.5", "", "N4", "00002322730", "LT", "B123"]
+        );
+        assert_eq!(segments.last().unwrap(), &Segment::new("CTT", &["1", "24"]));
+    }
+
+    #[test]
+    fn test_parse_855() {
+        let ack = parse_855(&set("855", &[
+            "BAK*00*AC*ATL0000000007*20260302",
+            "PO1*1*24*EA*12.50**N4*00002322730",
+            "ACK*IQ*20*EA",
+        ]))
+        .unwrap();
+
+        assert_eq!(ack.status, EdiAckStatus::AcceptedWithChanges);
+        assert_eq!(ack.po_number, "ATL0000000007");
+        assert_eq!(ack.lines[0].quantity, Some(20));
+        assert_eq!(ack.lines[0].product_id.as_deref(), Some("00002322730"));
+
+        assert!(parse_855(&set("855", &["BAK*00*ZZ*ATL1"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_856() {
+        let asn = parse_856(&set("856", &[
+            "BSN*00*SHP-991*20260305*1400",
+            "HL*1**S",
+            "TD5*B*2*UPSN*M*UPS Ground",
+            "REF*CN*1Z999AA10123456784",
+            "HL*2*1*O",
+            "PRF*ATL0000000007",
+            "HL*3*2*I",
+            "LIN**N4*00002322730*LT*B123",
+            "SN1**24*EA",
+            "DTM*036*20280131",
+        ]))
+        .unwrap();
+
+        assert_eq!(asn.po_numbers, vec!["ATL0000000007"]);
+        assert_eq!(asn.carrier.as_deref(), Some("UPS Ground"));
+        assert_eq!(asn.tracking_number.as_deref(), Some("1Z999AA10123456784"));
+        assert_eq!(asn.lines.len(), 1);
+        assert_eq!(asn.lines[0].lot_number.as_deref(), Some("B123"));
+        assert_eq!(asn.lines[0].quantity, Some(24));
+        assert_eq!(asn.lines[0].expiry_date, NaiveDate::from_ymd_opt(2028, 1, 31));
+    }
+
+    #[test]
+    fn test_parse_810() {
+        let invoice = parse_810(&set("810", &[
+            "BIG*20260306*INV-5521**ATL0000000007",
+            "IT1*1*24*EA*12.50**N4*00002322730",
+            "TDS*30000",
+        ]))
+        .unwrap();
+
+        assert_eq!(invoice.invoice_number, "INV-5521");
+        assert_eq!(invoice.total_amount, Decimal::new(30000, 2));
+        assert_eq!(invoice.lines[0].quantity, 24);
+        assert_eq!(parse_quantity("10.5"), None);
+    }
+}
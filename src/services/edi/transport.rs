@@ -0,0 +1,208 @@
+// EDI transport layer
+// AS2 (HTTPS POST with a synchronous, unsigned MDN) and SFTP (via the ERP SFTP client).
+// S/MIME signing and encryption are not supported; AS2 relies on TLS and basic auth.
+
+use std::time::Duration;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::services::erp::{SftpClient, SftpError};
+
+const AS2_TIMEOUT: Duration = Duration::from_secs(60);
+pub const AS2_CONTENT_TYPE: &str = "application/edi-x12";
+
+#[derive(Error, Debug)]
+pub enum EdiTransportError {
+    #[error("AS2 request failed: {0}")]
+    As2(String),
+
+    #[error("AS2 partner did not process the message: {0}")]
+    As2Disposition(String),
+
+    #[error(transparent)]
+    Sftp(#[from] SftpError),
+}
+
+pub type Result<T> = std::result::Result<T, EdiTransportError>;
+
+/// What happened to an outbound message
+#[derive(Debug, Clone)]
+pub struct TransmissionReceipt {
+    /// AS2 Message-ID or the remote SFTP path
+    pub reference: String,
+    /// Disposition line of the partner's MDN (AS2 only)
+    pub disposition: Option<String>,
+}
+
+// ============================================================================
+// AS2
+// ============================================================================
+
+#[derive(Debug, Clone)]
+pub struct As2Config {
+    pub url: String,
+    pub partner_as2_id: String,
+    pub atlas_as2_id: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+pub struct As2Client {
+    config: As2Config,
+    http: reqwest::Client,
+}
+
+impl As2Client {
+    pub fn new(config: As2Config) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(AS2_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        Self { config, http }
+    }
+
+    /// POST the interchange and require a "processed" disposition in the synchronous MDN
+    pub async fn send(&self, file_name: &str, payload: &str) -> Result<TransmissionReceipt> {
+        let message_id = format!("<{}@{}>", Uuid::new_v4(), self.config.atlas_as2_id);
+
+        let mut request = self.http
+            .post(&self.config.url)
+            .header("AS2-Version", "1.2")
+            .header("AS2-From", quote_as2_id(&self.config.atlas_as2_id))
+            .header("AS2-To", quote_as2_id(&self.config.partner_as2_id))
+            .header("Message-ID", &message_id)
+            .header("Subject", file_name)
+            .header("Content-Type", AS2_CONTENT_TYPE)
+            .header("Content-Disposition", format!("attachment; filename=\"{}\"", file_name))
+            .header("Disposition-Notification-To", &self.config.atlas_as2_id)
+            .body(payload.to_string());
+
+        if let Some(username) = &self.config.username {
+            request = request.basic_auth(username, self.config.password.as_deref());
+        }
+
+        let response = request.send().await.map_err(|e| EdiTransportError::As2(e.to_string()))?;
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+
+        if !status.is_success() {
+            return Err(EdiTransportError::As2(format!("HTTP {}: {}", status, truncate(&body, 500))));
+        }
+
+        let disposition = mdn_disposition(&body);
+        match &disposition {
+            Some(line) if is_processed(line) => Ok(TransmissionReceipt { reference: message_id, disposition }),
+            Some(line) => Err(EdiTransportError::As2Disposition(line.clone())),
+            // Some gateways acknowledge with an empty 200; treat as delivered
+            None => Ok(TransmissionReceipt { reference: message_id, disposition: None }),
+        }
+    }
+}
+
+/// AS2 identifiers with spaces must be quoted (RFC 4130 section 6.2)
+fn quote_as2_id(id: &str) -> String {
+    if id.contains(' ') {
+        format!("\"{}\"", id)
+    } else {
+        id.to_string()
+    }
+}
+
+/// The `Disposition:` value of an MDN body, if any
+fn mdn_disposition(body: &str) -> Option<String> {
+    body.lines()
+        .find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            name.trim().eq_ignore_ascii_case("disposition").then(|| value.trim().to_string())
+        })
+}
+
+fn is_processed(disposition: &str) -> bool {
+    let status = disposition.rsplit(';').next().unwrap_or("").trim().to_ascii_lowercase();
+    status.starts_with("processed") && !status.contains("error") && !status.contains("failed")
+}
+
+/// Synchronous MDN answering an inbound AS2 message: (content type, body)
+pub fn build_mdn(original_message_id: &str, atlas_as2_id: &str, error: Option<&str>) -> (String, String) {
+    let boundary = format!("----=_MDN_{}", Uuid::new_v4().simple());
+    let disposition = match error {
+        None => "automatic-action/MDN-sent-automatically; processed".to_string(),
+        Some(_) => "automatic-action/MDN-sent-automatically; processed/error: unexpected-processing-error".to_string(),
+    };
+    let text = match error {
+        None => "The AS2 message has been received and processed.".to_string(),
+        Some(reason) => format!("The AS2 message could not be processed: {}", reason),
+    };
+
+    let body = format!(
+        "--{b}\r\nContent-Type: text/plain\r\n\r\n{text}\r\n\
+         --{b}\r\nContent-Type: message/disposition-notification\r\n\r\n\
+         Reporting-UA: Atlas PharmaTech\r\n\
+         Final-Recipient: rfc822; {recipient}\r\n\
+         Original-Message-ID: {original}\r\n\
+         Disposition: {disposition}\r\n\r\n\
+         --{b}--\r\n",
+        b = boundary,
+        text = text,
+        recipient = atlas_as2_id,
+        original = original_message_id,
+        disposition = disposition,
+    );
+
+    (format!("multipart/report; report-type=disposition-notification; boundary=\"{}\"", boundary), body)
+}
+
+// ============================================================================
+// Transport Selection
+// ============================================================================
+
+pub enum EdiTransportClient {
+    As2(As2Client),
+    Sftp(SftpClient),
+}
+
+impl EdiTransportClient {
+    pub async fn send(&self, file_name: &str, payload: &str) -> Result<TransmissionReceipt> {
+        match self {
+            EdiTransportClient::As2(client) => client.send(file_name, payload).await,
+            EdiTransportClient::Sftp(client) => {
+                let path = client.upload(file_name, payload.as_bytes().to_vec()).await?;
+                Ok(TransmissionReceipt { reference: path, disposition: None })
+            }
+        }
+    }
+}
+
+fn truncate(value: &str, max: usize) -> String {
+    value.chars().take(max).collect()
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mdn_disposition() {
+        let ok = "Original-Message-ID: <1@x>\r\nDisposition: automatic-action/MDN-sent-automatically; processed\r\n";
+        let failed = "Disposition: automatic-action/MDN-sent-automatically; processed/error: decryption-failed";
+
+        assert!(is_processed(&mdn_disposition(ok).unwrap()));
+        assert!(!is_processed(&mdn_disposition(failed).unwrap()));
+        assert_eq!(mdn_disposition("OK"), None);
+    }
+
+    #[test]
+    fn test_build_mdn_round_trips() {
+        let (content_type, body) = build_mdn("<abc@partner>", "ATLASPHARMA", None);
+        assert!(content_type.starts_with("multipart/report"));
+        assert!(body.contains("Original-Message-ID: <abc@partner>"));
+        assert!(is_processed(&mdn_disposition(&body).unwrap()));
+
+        let (_, body) = build_mdn("<abc@partner>", "ATLASPHARMA", Some("bad envelope"));
+        assert!(!is_processed(&mdn_disposition(&body).unwrap()));
+    }
+}
@@ -0,0 +1,447 @@
+// ANSI X12 envelope codec
+// Parses ISA/GS/ST interchanges with the delimiters declared in the ISA segment and
+// writes single-transaction-set interchanges for outbound documents
+
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+
+pub const ELEMENT_SEPARATOR: char = '*';
+pub const COMPONENT_SEPARATOR: char = '>';
+pub const REPETITION_SEPARATOR: char = '^';
+pub const SEGMENT_TERMINATOR: char = '~';
+
+/// The ISA segment is fixed-width: 106 characters including its terminator
+const ISA_LENGTH: usize = 106;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum X12Error {
+    #[error("Not an X12 interchange: {0}")]
+    InvalidEnvelope(String),
+
+    #[error("Unexpected segment {found} (expected {expected})")]
+    UnexpectedSegment { expected: String, found: String },
+
+    #[error("Control number mismatch in {segment}: {header} vs {trailer}")]
+    ControlNumberMismatch { segment: String, header: String, trailer: String },
+
+    #[error("Count mismatch in {segment}: declared {declared}, found {actual}")]
+    CountMismatch { segment: String, declared: usize, actual: usize },
+
+    #[error("Invalid {transaction_set}: {message}")]
+    InvalidTransactionSet { transaction_set: String, message: String },
+}
+
+pub type Result<T> = std::result::Result<T, X12Error>;
+
+// ============================================================================
+// Segments
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+    pub id: String,
+    pub elements: Vec<String>,
+}
+
+impl Segment {
+    pub fn new(id: &str, elements: &[&str]) -> Self {
+        Self {
+            id: id.to_string(),
+            elements: elements.iter().map(|e| e.to_string()).collect(),
+        }
+    }
+
+    /// Element by its X12 position (BEG03 -> `element(3)`); empty when absent
+    pub fn element(&self, position: usize) -> &str {
+        position
+            .checked_sub(1)
+            .and_then(|i| self.elements.get(i))
+            .map(String::as_str)
+            .unwrap_or("")
+    }
+
+    /// Element by position, `None` when absent or empty
+    pub fn value(&self, position: usize) -> Option<&str> {
+        Some(self.element(position).trim()).filter(|v| !v.is_empty())
+    }
+
+    fn write(&self, out: &mut String) {
+        out.push_str(&self.id);
+        // Trailing empty elements are omitted
+        let used = self.elements.iter().rposition(|e| !e.is_empty()).map_or(0, |i| i + 1);
+        for element in &self.elements[..used] {
+            out.push(ELEMENT_SEPARATOR);
+            out.push_str(&sanitize(element));
+        }
+        out.push(SEGMENT_TERMINATOR);
+    }
+}
+
+/// Remove delimiter characters from a data element
+pub fn sanitize(value: &str) -> String {
+    value
+        .chars()
+        .filter(|c| ![ELEMENT_SEPARATOR, COMPONENT_SEPARATOR, REPETITION_SEPARATOR, SEGMENT_TERMINATOR].contains(c))
+        .filter(|c| !c.is_control())
+        .collect()
+}
+
+// ============================================================================
+// Parsed Interchange
+// ============================================================================
+
+#[derive(Debug, Clone)]
+pub struct Interchange {
+    pub sender_qualifier: String,
+    pub sender_id: String,
+    pub receiver_qualifier: String,
+    pub receiver_id: String,
+    pub control_number: String,
+    pub version: String,
+    pub test: bool,
+    pub groups: Vec<FunctionalGroup>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FunctionalGroup {
+    pub functional_id: String,
+    pub sender_id: String,
+    pub receiver_id: String,
+    pub control_number: String,
+    pub version: String,
+    pub transaction_sets: Vec<TransactionSet>,
+}
+
+/// The segments of one transaction set between (excluding) ST and SE
+#[derive(Debug, Clone)]
+pub struct TransactionSet {
+    pub id: String,
+    pub control_number: String,
+    pub segments: Vec<Segment>,
+}
+
+impl TransactionSet {
+    pub fn find(&self, id: &str) -> Option<&Segment> {
+        self.segments.iter().find(|s| s.id == id)
+    }
+
+    pub fn invalid(&self, message: impl Into<String>) -> X12Error {
+        X12Error::InvalidTransactionSet {
+            transaction_set: self.id.clone(),
+            message: message.into(),
+        }
+    }
+}
+
+pub fn parse(input: &str) -> Result<Interchange> {
+    let input = input.trim_start_matches('\u{feff}').trim_start();
+    let isa_chars: Vec<char> = input.chars().take(ISA_LENGTH).collect();
+    if !input.starts_with("ISA") || isa_chars.len() < ISA_LENGTH {
+        return Err(X12Error::InvalidEnvelope("missing ISA segment".to_string()));
+    }
+
+    let element_separator = isa_chars[3];
+    let segment_terminator = isa_chars[ISA_LENGTH - 1];
+    if element_separator.is_alphanumeric() || segment_terminator.is_alphanumeric() {
+        return Err(X12Error::InvalidEnvelope("invalid delimiters in ISA".to_string()));
+    }
+
+    let mut segments = input
+        .split(segment_terminator)
+        .map(|s| s.trim_matches(|c: char| c == '\r' || c == '\n'))
+        .filter(|s| !s.trim().is_empty())
+        .map(|raw| {
+            let mut parts = raw.split(element_separator);
+            Segment {
+                id: parts.next().unwrap_or_default().trim().to_string(),
+                elements: parts.map(str::to_string).collect(),
+            }
+        });
+
+    let isa = expect(segments.next(), "ISA")?;
+    if isa.elements.len() != 16 {
+        return Err(X12Error::InvalidEnvelope(format!("ISA has {} elements, expected 16", isa.elements.len())));
+    }
+
+    let mut interchange = Interchange {
+        sender_qualifier: isa.element(5).trim().to_string(),
+        sender_id: isa.element(6).trim().to_string(),
+        receiver_qualifier: isa.element(7).trim().to_string(),
+        receiver_id: isa.element(8).trim().to_string(),
+        control_number: isa.element(13).trim().to_string(),
+        version: isa.element(12).trim().to_string(),
+        test: isa.element(15).trim() == "T",
+        groups: Vec::new(),
+    };
+
+    loop {
+        let segment = segments
+            .next()
+            .ok_or_else(|| X12Error::InvalidEnvelope("missing IEA segment".to_string()))?;
+
+        match segment.id.as_str() {
+            "GS" => {
+                let mut group = FunctionalGroup {
+                    functional_id: segment.element(1).to_string(),
+                    sender_id: segment.element(2).trim().to_string(),
+                    receiver_id: segment.element(3).trim().to_string(),
+                    control_number: segment.element(6).trim().to_string(),
+                    version: segment.element(8).trim().to_string(),
+                    transaction_sets: Vec::new(),
+                };
+
+                loop {
+                    let segment = segments
+                        .next()
+                        .ok_or_else(|| X12Error::InvalidEnvelope("missing GE segment".to_string()))?;
+
+                    match segment.id.as_str() {
+                        "ST" => group.transaction_sets.push(parse_transaction_set(&segment, &mut segments)?),
+                        "GE" => {
+                            check_control(&segment, 2, &group.control_number)?;
+                            check_count(&segment, group.transaction_sets.len())?;
+                            break;
+                        }
+                        other => {
+                            return Err(X12Error::UnexpectedSegment {
+                                expected: "ST or GE".to_string(),
+                                found: other.to_string(),
+                            })
+                        }
+                    }
+                }
+
+                interchange.groups.push(group);
+            }
+            "IEA" => {
+                check_control(&segment, 2, &interchange.control_number)?;
+                check_count(&segment, interchange.groups.len())?;
+                return Ok(interchange);
+            }
+            other => {
+                return Err(X12Error::UnexpectedSegment {
+                    expected: "GS or IEA".to_string(),
+                    found: other.to_string(),
+                })
+            }
+        }
+    }
+}
+
+fn parse_transaction_set(st: &Segment, segments: &mut impl Iterator<Item = Segment>) -> Result<TransactionSet> {
+    let mut set = TransactionSet {
+        id: st.element(1).trim().to_string(),
+        control_number: st.element(2).trim().to_string(),
+        segments: Vec::new(),
+    };
+
+    for segment in segments.by_ref() {
+        if segment.id == "SE" {
+            check_control(&segment, 2, &set.control_number)?;
+            // SE01 counts every segment including ST and SE
+            check_count(&segment, set.segments.len() + 2)?;
+            return Ok(set);
+        }
+        set.segments.push(segment);
+    }
+
+    Err(X12Error::InvalidEnvelope(format!("transaction set {} has no SE segment", set.control_number)))
+}
+
+fn expect(segment: Option<Segment>, id: &str) -> Result<Segment> {
+    match segment {
+        Some(segment) if segment.id == id => Ok(segment),
+        Some(segment) => Err(X12Error::UnexpectedSegment { expected: id.to_string(), found: segment.id }),
+        None => Err(X12Error::InvalidEnvelope(format!("missing {} segment", id))),
+    }
+}
+
+fn check_control(trailer: &Segment, position: usize, header: &str) -> Result<()> {
+    let value = trailer.element(position).trim();
+    // Numeric control numbers may be zero-padded differently in header and trailer
+    let same = value == header
+        || matches!((value.parse::<u64>(), header.parse::<u64>()), (Ok(a), Ok(b)) if a == b);
+    if same {
+        Ok(())
+    } else {
+        Err(X12Error::ControlNumberMismatch {
+            segment: trailer.id.clone(),
+            header: header.to_string(),
+            trailer: value.to_string(),
+        })
+    }
+}
+
+fn check_count(trailer: &Segment, actual: usize) -> Result<()> {
+    let declared = trailer.element(1).trim().parse::<usize>().unwrap_or(usize::MAX);
+    if declared == actual {
+        Ok(())
+    } else {
+        Err(X12Error::CountMismatch { segment: trailer.id.clone(), declared, actual })
+    }
+}
+
+// ============================================================================
+// Writing
+// ============================================================================
+
+/// Envelope of an outbound interchange
+#[derive(Debug, Clone)]
+pub struct OutboundEnvelope {
+    pub sender_qualifier: String,
+    pub sender_id: String,
+    pub receiver_qualifier: String,
+    pub receiver_id: String,
+    pub gs_sender_id: String,
+    pub gs_receiver_id: String,
+    /// Used for ISA13, GS06 and ST02
+    pub control_number: u32,
+    /// "004010" or "005010"
+    pub version: String,
+    pub test: bool,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl OutboundEnvelope {
+    pub fn control_number_str(&self) -> String {
+        format!("{:09}", self.control_number)
+    }
+}
+
+/// Write one transaction set (body segments, without ST/SE) in a complete interchange
+pub fn write_interchange(envelope: &OutboundEnvelope, transaction_set: &str, functional_id: &str, body: &[Segment]) -> String {
+    let control = envelope.control_number_str();
+    let is_5010 = envelope.version.starts_with("005");
+    let date6 = envelope.timestamp.format("%y%m%d").to_string();
+    let date8 = envelope.timestamp.format("%Y%m%d").to_string();
+    let time = envelope.timestamp.format("%H%M").to_string();
+
+    // ISA is fixed-width and carries the delimiters, so it is written by hand
+    let mut out = String::from("ISA");
+    let isa_elements = [
+        "00".to_string(),
+        " ".repeat(10),
+        "00".to_string(),
+        " ".repeat(10),
+        pad(&envelope.sender_qualifier, 2),
+        pad(&envelope.sender_id, 15),
+        pad(&envelope.receiver_qualifier, 2),
+        pad(&envelope.receiver_id, 15),
+        date6,
+        time.clone(),
+        if is_5010 { REPETITION_SEPARATOR.to_string() } else { "U".to_string() },
+        if is_5010 { "00501".to_string() } else { "00401".to_string() },
+        control.clone(),
+        "0".to_string(),
+        if envelope.test { "T".to_string() } else { "P".to_string() },
+        COMPONENT_SEPARATOR.to_string(),
+    ];
+    for element in &isa_elements {
+        out.push(ELEMENT_SEPARATOR);
+        out.push_str(element);
+    }
+    out.push(SEGMENT_TERMINATOR);
+
+    Segment::new("GS", &[
+        functional_id,
+        &envelope.gs_sender_id,
+        &envelope.gs_receiver_id,
+        &date8,
+        &time,
+        &envelope.control_number.to_string(),
+        "X",
+        &envelope.version,
+    ])
+    .write(&mut out);
+
+    Segment::new("ST", &[transaction_set, &control]).write(&mut out);
+    for segment in body {
+        segment.write(&mut out);
+    }
+    Segment::new("SE", &[&(body.len() + 2).to_string(), &control]).write(&mut out);
+    Segment::new("GE", &["1", &envelope.control_number.to_string()]).write(&mut out);
+    Segment::new("IEA", &["1", &control]).write(&mut out);
+
+    out
+}
+
+/// Fixed-width ISA element: truncated or right-padded with spaces
+fn pad(value: &str, width: usize) -> String {
+    let value: String = sanitize(value).chars().take(width).collect();
+    format!("{:<width$}", value, width = width)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn envelope() -> OutboundEnvelope {
+        OutboundEnvelope {
+            sender_qualifier: "ZZ".to_string(),
+            sender_id: "ATLASPHARMA".to_string(),
+            receiver_qualifier: "01".to_string(),
+            receiver_id: "123456789".to_string(),
+            gs_sender_id: "ATLASPHARMA".to_string(),
+            gs_receiver_id: "123456789".to_string(),
+            control_number: 42,
+            version: "004010".to_string(),
+            test: true,
+            timestamp: Utc.with_ymd_and_hms(2026, 3, 1, 9, 30, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_write_then_parse_round_trip() {
+        let body = vec![
+            Segment::new("BEG", &["00", "SA", "ATL0000000001", "", "20260301"]),
+            Segment::new("PO1", &["1", "10", "EA", "12.50", "", "N4", "00002322730"]),
+            Segment::new("CTT", &["1", "10"]),
+        ];
+        let x12 = write_interchange(&envelope(), "850", "PO", &body);
+
+        assert!(x12.starts_with("ISA*00*          *00*          *ZZ*ATLASPHARMA    *01*123456789      *260301*0930*U*00401*000000042*0*T*>~"));
+        assert!(x12.contains("GS*PO*ATLASPHARMA*123456789*20260301*0930*42*X*004010~"));
+        assert!(x12.contains("SE*5*000000042~"));
+
+        let interchange = parse(&x12).unwrap();
+        assert_eq!(interchange.sender_id, "ATLASPHARMA");
+        assert_eq!(interchange.receiver_qualifier, "01");
+        assert!(interchange.test);
+
+        let set = &interchange.groups[0].transaction_sets[0];
+        assert_eq!(set.id, "850");
+        assert_eq!(set.segments.len(), 3);
+        assert_eq!(set.find("BEG").unwrap().element(3), "ATL0000000001");
+        assert_eq!(set.find("BEG").unwrap().value(4), None);
+    }
+
+    #[test]
+    fn test_parse_honours_declared_delimiters() {
+        let x12 = write_interchange(&envelope(), "855", "PR", &[Segment::new("BAK", &["00", "AD", "ATL1", "20260302"])])
+            .replace('*', "|")
+            .replace('~', "\n");
+
+        let interchange = parse(&x12).unwrap();
+        let set = &interchange.groups[0].transaction_sets[0];
+        assert_eq!(set.find("BAK").unwrap().element(2), "AD");
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_counts() {
+        let x12 = write_interchange(&envelope(), "850", "PO", &[Segment::new("BEG", &["00", "SA", "ATL1"])])
+            .replace("SE*3*", "SE*4*");
+
+        assert!(matches!(parse(&x12), Err(X12Error::CountMismatch { .. })));
+        assert!(matches!(parse("hello"), Err(X12Error::InvalidEnvelope(_))));
+    }
+
+    #[test]
+    fn test_sanitize_strips_delimiters() {
+        assert_eq!(sanitize("ACME*Pharma~Inc>"), "ACMEPharmaInc");
+    }
+}
@@ -0,0 +1,520 @@
+/// Electronic Signature Service (21 CFR Part 11)
+///
+/// Signing sequences and signatures of regulatory documents. Each signature requires
+/// re-authentication (password, and a TOTP code when MFA is enabled), is made in the
+/// order of the sequence with the meaning of the signer's slot, and signs a manifest
+/// (printed name, meaning, timestamp, document content hash) with the signer's Ed25519
+/// key. Signatures are recorded in the regulatory document ledger; the last one in the
+/// sequence approves the document and supersedes its earlier approved versions.
+/// `verify` re-checks every manifest.
+
+use crate::{
+    middleware::error_handling::{AppError, Result},
+    models::electronic_signature::*,
+    repositories::UserRepository,
+    services::{DocumentVersionService, Ed25519SignatureService, MfaTotpService},
+};
+use anyhow::anyhow;
+use chrono::Utc;
+use sha2::{Digest, Sha256};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+/// Document statuses that still accept signatures
+const SIGNABLE_STATUSES: [&str; 2] = ["draft", "pending_approval"];
+
+/// Failed signing passwords within SIGNATURE_LOCKOUT_MINUTES that lock a signer out
+const MAX_FAILED_SIGNATURE_ATTEMPTS: i64 = 5;
+const SIGNATURE_LOCKOUT_MINUTES: i32 = 15;
+
+#[derive(Debug, FromRow)]
+struct SignableDocument {
+    id: Uuid,
+    document_type: String,
+    document_number: String,
+    content: serde_json::Value,
+    content_hash: String,
+    status: String,
+    generated_by: Uuid,
+}
+
+pub struct ElectronicSignatureService {
+    db_pool: PgPool,
+    user_repo: UserRepository,
+    signature_service: Ed25519SignatureService,
+    mfa_service: MfaTotpService,
+    version_service: DocumentVersionService,
+}
+
+impl ElectronicSignatureService {
+    pub fn new(db_pool: PgPool, encryption_key: &str) -> Result<Self> {
+        Ok(Self {
+            user_repo: UserRepository::new(db_pool.clone(), encryption_key)?,
+            signature_service: Ed25519SignatureService::new(db_pool.clone(), encryption_key)?,
+            mfa_service: MfaTotpService::new(db_pool.clone(), encryption_key, "Atlas Pharma".to_string())?,
+            version_service: DocumentVersionService::new(db_pool.clone(), encryption_key)?,
+            db_pool,
+        })
+    }
+
+    /// Replace the signing sequence of a document the user generated. Not possible once
+    /// anyone has signed.
+    pub async fn set_sequence(
+        &self,
+        document_id: Uuid,
+        user_id: Uuid,
+        request: SetSigningSequenceRequest,
+    ) -> Result<SigningStatus> {
+        let document = self.load_document(document_id).await?;
+        if document.generated_by != user_id {
+            return Err(AppError::Forbidden("Only the document's author sets its signers".to_string()));
+        }
+        Self::ensure_signable(&document)?;
+
+        let existing = self.signatures(document_id).await?;
+        if existing.iter().any(|s| s.is_signed()) {
+            return Err(AppError::BadRequest(
+                "The signing sequence cannot be changed after the first signature".to_string(),
+            ));
+        }
+
+        let mut tx = self.db_pool.begin().await?;
+        sqlx::query("DELETE FROM regulatory_document_signatures WHERE document_id = $1 AND status = 'pending'")
+            .bind(document_id)
+            .execute(&mut *tx)
+            .await?;
+
+        for (i, slot) in request.signers.iter().enumerate() {
+            sqlx::query(
+                r#"
+                INSERT INTO regulatory_document_signatures
+                    (document_id, sequence_number, signer_id, meaning, requested_by)
+                VALUES ($1, $2, $3, $4, $5)
+                "#,
+            )
+            .bind(document_id)
+            .bind(i as i32 + 1)
+            .bind(slot.user_id)
+            .bind(slot.meaning.as_str())
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        sqlx::query("UPDATE regulatory_documents SET status = 'pending_approval', updated_at = NOW() WHERE id = $1")
+            .bind(document_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        tracing::info!(
+            "Signing sequence of document {} set by user {}: {} signers",
+            document_id,
+            user_id,
+            request.signers.len()
+        );
+
+        self.status(document_id, user_id).await
+    }
+
+    /// Signing sequence and manifests; visible to the author and the signers
+    pub async fn status(&self, document_id: Uuid, user_id: Uuid) -> Result<SigningStatus> {
+        let document = self.load_document(document_id).await?;
+        let signatures = self.signatures(document_id).await?;
+
+        if document.generated_by != user_id && !signatures.iter().any(|s| s.signer_id == user_id) {
+            return Err(AppError::Forbidden("Access denied".to_string()));
+        }
+
+        let next_signer_id = next_pending_signature(&signatures).map(|s| s.signer_id);
+        Ok(SigningStatus {
+            document_id,
+            document_status: document.status,
+            signatures,
+            next_signer_id,
+        })
+    }
+
+    /// Sign a document as the next signer in its sequence. A document without a sequence
+    /// gets a single approval signature (the former one-step approval).
+    pub async fn sign(&self, document_id: Uuid, user_id: Uuid, request: SignDocumentRequest) -> Result<SigningStatus> {
+        let signer = self
+            .user_repo
+            .find_by_id(user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+        let auth_method = self.reauthenticate(user_id, document_id, &signer.password_hash, &request).await?;
+
+        let document = self.load_document(document_id).await?;
+        Self::ensure_signable(&document)?;
+
+        // Never sign content that no longer matches its recorded hash
+        let content_json = serde_json::to_string(&document.content)?;
+        if hex::encode(Sha256::digest(content_json.as_bytes())) != document.content_hash {
+            return Err(AppError::BadRequest(
+                "Document content does not match its recorded hash; it cannot be signed".to_string(),
+            ));
+        }
+
+        let mut signatures = self.signatures(document_id).await?;
+        if signatures.is_empty() {
+            if request.meaning != SignatureMeaning::Approval {
+                return Err(AppError::BadRequest(
+                    "Documents without a signing sequence take a single approval signature".to_string(),
+                ));
+            }
+            sqlx::query(
+                r#"
+                INSERT INTO regulatory_document_signatures
+                    (document_id, sequence_number, signer_id, meaning, requested_by)
+                VALUES ($1, 1, $2, 'approval', $2)
+                "#,
+            )
+            .bind(document_id)
+            .bind(user_id)
+            .execute(&self.db_pool)
+            .await?;
+            signatures = self.signatures(document_id).await?;
+        }
+
+        let slot = next_pending_signature(&signatures)
+            .ok_or_else(|| AppError::BadRequest("All signatures have been collected".to_string()))?;
+        if slot.signer_id != user_id {
+            return Err(AppError::Forbidden(if signatures.iter().any(|s| s.signer_id == user_id && !s.is_signed()) {
+                "Earlier signers in the sequence have not signed yet".to_string()
+            } else {
+                "You are not the next signer of this document".to_string()
+            }));
+        }
+        if slot.meaning != request.meaning.as_str() {
+            return Err(AppError::BadRequest(format!(
+                "This signature is for {}, not {}",
+                slot.meaning,
+                request.meaning.as_str()
+            )));
+        }
+        let is_last = slot.sequence_number == signatures.iter().map(|s| s.sequence_number).max().unwrap_or(0);
+
+        if !self.signature_service.has_keypair(user_id).await? {
+            self.signature_service.generate_user_keypair(user_id).await?;
+            tracing::info!("Generated Ed25519 keypair for user {}", user_id);
+        }
+        let public_key = self
+            .signature_service
+            .get_user_public_key(user_id)
+            .await?
+            .ok_or_else(|| anyhow!("User has no public key"))?;
+
+        let manifest = SignatureManifest {
+            document_id,
+            document_number: document.document_number.clone(),
+            content_hash: document.content_hash.clone(),
+            sequence_number: slot.sequence_number,
+            signer_id: user_id,
+            signer_name: signer.contact_person.clone(),
+            meaning: request.meaning,
+            statement: request.meaning.statement().to_string(),
+            signed_at: Utc::now(),
+        };
+        let manifest_value = manifest.to_value();
+        let (signature, manifest_hash) = self
+            .signature_service
+            .sign_document(user_id, &manifest_text(&manifest_value))
+            .await?;
+
+        let mut tx = self.db_pool.begin().await?;
+        let signed = sqlx::query(
+            r#"
+            UPDATE regulatory_document_signatures
+            SET status = 'signed', signer_name = $2, signed_at = $3, content_hash = $4,
+                manifest = $5, manifest_hash = $6, signature = $7, signature_public_key = $8,
+                auth_method = $9
+            WHERE id = $1 AND status = 'pending'
+            "#,
+        )
+        .bind(slot.id)
+        .bind(&manifest.signer_name)
+        .bind(manifest.signed_at)
+        .bind(&document.content_hash)
+        .bind(&manifest_value)
+        .bind(&manifest_hash)
+        .bind(&signature)
+        .bind(&public_key)
+        .bind(auth_method)
+        .execute(&mut *tx)
+        .await?;
+        if signed.rows_affected() == 0 {
+            return Err(AppError::BadRequest("This signature has already been made".to_string()));
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO regulatory_document_ledger (
+                document_id, document_type, operation, operation_description, content_hash,
+                content_snapshot, signature, signature_public_key, user_id, user_email, user_name, metadata
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            "#,
+        )
+        .bind(document_id)
+        .bind(&document.document_type)
+        .bind(if is_last { "approved" } else { "signed" })
+        .bind(format!("Electronic signature {} ({})", slot.sequence_number, request.meaning.statement()))
+        .bind(&document.content_hash)
+        .bind(&manifest_value)
+        .bind(&signature)
+        .bind(&public_key)
+        .bind(user_id)
+        .bind(&signer.email)
+        .bind(&manifest.signer_name)
+        .bind(serde_json::json!({
+            "signature_id": slot.id,
+            "sequence_number": slot.sequence_number,
+            "meaning": request.meaning,
+            "manifest_hash": manifest_hash,
+            "auth_method": auth_method,
+        }))
+        .execute(&mut *tx)
+        .await?;
+
+        if is_last {
+            sqlx::query(
+                r#"
+                UPDATE regulatory_documents
+                SET status = 'approved', approved_by = $2, approved_signature = $3,
+                    approved_at = $4, updated_at = NOW()
+                WHERE id = $1
+                "#,
+            )
+            .bind(document_id)
+            .bind(user_id)
+            .bind(&signature)
+            .bind(manifest.signed_at)
+            .execute(&mut *tx)
+            .await?;
+
+            self.version_service
+                .supersede_earlier_versions(&mut tx, document_id, user_id)
+                .await?;
+        } else if document.status == "draft" {
+            sqlx::query("UPDATE regulatory_documents SET status = 'pending_approval', updated_at = NOW() WHERE id = $1")
+                .bind(document_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+
+        tracing::info!(
+            "Document {} signed by user {} ({}, signature {}/{}{})",
+            document_id,
+            user_id,
+            request.meaning.as_str(),
+            slot.sequence_number,
+            signatures.len(),
+            if is_last { ", approved" } else { "" }
+        );
+
+        self.status(document_id, user_id).await
+    }
+
+    /// Re-check every signed manifest: it hashes to the recorded hash, covers the
+    /// document's current content hash, and carries a valid Ed25519 signature
+    pub async fn verify(&self, document_id: Uuid) -> Result<SignatureVerification> {
+        let document = self.load_document(document_id).await?;
+        let signatures = self.signatures(document_id).await?;
+
+        let mut invalid_signature_ids = Vec::new();
+        let mut signature_count = 0;
+        for signature in signatures.iter().filter(|s| s.is_signed()) {
+            signature_count += 1;
+
+            let signature_valid = match (&signature.manifest_hash, &signature.signature, &signature.signature_public_key) {
+                (Some(hash), Some(sig), Some(public_key)) => {
+                    manifest_matches(signature, &document.content_hash)
+                        && self.signature_service.verify_signature(hash, sig, public_key).unwrap_or(false)
+                }
+                _ => false,
+            };
+            if !signature_valid {
+                invalid_signature_ids.push(signature.id);
+            }
+        }
+
+        Ok(SignatureVerification {
+            signature_count,
+            valid: invalid_signature_ids.is_empty(),
+            invalid_signature_ids,
+        })
+    }
+
+    // ========================================================================
+    // PRIVATE HELPERS
+    // ========================================================================
+
+    /// Password, plus a TOTP code when the signer has MFA enabled. Every password check is
+    /// logged to signature_reauth_log; too many failures lock the signer out for a while.
+    async fn reauthenticate(
+        &self,
+        user_id: Uuid,
+        document_id: Uuid,
+        password_hash: &str,
+        request: &SignDocumentRequest,
+    ) -> Result<&'static str> {
+        let recent_failures = self.recent_failed_attempts(user_id).await?;
+        let checked = check_signing_password(&request.password, password_hash, recent_failures);
+        let result = match &checked {
+            Ok(()) => "success",
+            Err(AppError::TooManyRequests(_)) => "locked_out",
+            Err(_) => "invalid_password",
+        };
+        self.log_reauth_attempt(user_id, document_id, result).await?;
+        if let Err(e) = checked {
+            tracing::warn!(
+                "Electronic signature re-authentication failed for user {} ({})",
+                user_id,
+                result
+            );
+            return Err(e);
+        }
+
+        if !self.mfa_service.is_mfa_enabled(user_id).await? {
+            return Ok("password");
+        }
+
+        if !self.mfa_service.check_rate_limit(user_id).await? {
+            self.mfa_service
+                .log_verification_attempt(user_id, "totp", "rate_limited", None, None)
+                .await?;
+            return Err(AppError::TooManyRequests(
+                "Too many verification attempts. Please try again later.".to_string(),
+            ));
+        }
+
+        let code = request
+            .totp_code
+            .as_deref()
+            .ok_or_else(|| AppError::BadRequest("An MFA code is required to sign".to_string()))?;
+        let secret = self
+            .mfa_service
+            .get_user_totp_secret(user_id)
+            .await?
+            .ok_or_else(|| AppError::BadRequest("MFA is not configured".to_string()))?;
+
+        if !self.mfa_service.verify_totp_code(&secret, code)? {
+            self.mfa_service
+                .log_verification_attempt(user_id, "totp", "invalid_code", None, None)
+                .await?;
+            return Err(AppError::Forbidden("Re-authentication failed".to_string()));
+        }
+        self.mfa_service
+            .log_verification_attempt(user_id, "totp", "success", None, None)
+            .await?;
+
+        Ok("password_totp")
+    }
+
+    async fn recent_failed_attempts(&self, user_id: Uuid) -> Result<i64> {
+        let count = sqlx::query_scalar::<_, i64>(
+            r#"
+            SELECT COUNT(*) FROM signature_reauth_log
+            WHERE user_id = $1 AND result = 'invalid_password'
+              AND created_at > NOW() - make_interval(mins => $2)
+            "#,
+        )
+        .bind(user_id)
+        .bind(SIGNATURE_LOCKOUT_MINUTES)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        Ok(count)
+    }
+
+    async fn log_reauth_attempt(&self, user_id: Uuid, document_id: Uuid, result: &str) -> Result<()> {
+        sqlx::query("INSERT INTO signature_reauth_log (user_id, document_id, result) VALUES ($1, $2, $3)")
+            .bind(user_id)
+            .bind(document_id)
+            .bind(result)
+            .execute(&self.db_pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn load_document(&self, document_id: Uuid) -> Result<SignableDocument> {
+        sqlx::query_as::<_, SignableDocument>(
+            r#"
+            SELECT id, document_type, document_number, content, content_hash, status, generated_by
+            FROM regulatory_documents
+            WHERE id = $1
+            "#,
+        )
+        .bind(document_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Document not found".to_string()))
+    }
+
+    async fn signatures(&self, document_id: Uuid) -> Result<Vec<DocumentSignature>> {
+        let signatures = sqlx::query_as::<_, DocumentSignature>(
+            "SELECT * FROM regulatory_document_signatures WHERE document_id = $1 ORDER BY sequence_number",
+        )
+        .bind(document_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(signatures)
+    }
+
+    fn ensure_signable(document: &SignableDocument) -> Result<()> {
+        if !SIGNABLE_STATUSES.contains(&document.status.as_str()) {
+            return Err(AppError::BadRequest(format!(
+                "Document {} is {} and cannot be signed",
+                document.id, document.status
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Check a signing password, unless the signer is locked out by `recent_failures`
+fn check_signing_password(password: &str, password_hash: &str, recent_failures: i64) -> Result<()> {
+    if recent_failures >= MAX_FAILED_SIGNATURE_ATTEMPTS {
+        return Err(AppError::TooManyRequests(format!(
+            "Too many failed signature attempts. Try again in {} minutes.",
+            SIGNATURE_LOCKOUT_MINUTES
+        )));
+    }
+    if !bcrypt::verify(password, password_hash)? {
+        return Err(AppError::Forbidden("Re-authentication failed".to_string()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repeated_wrong_passwords_lock_out_the_signer() {
+        let hash = bcrypt::hash("correct horse", 4).unwrap();
+        assert!(check_signing_password("correct horse", &hash, 0).is_ok());
+
+        let mut failures = 0;
+        while failures < MAX_FAILED_SIGNATURE_ATTEMPTS {
+            assert!(matches!(
+                check_signing_password("wrong", &hash, failures),
+                Err(AppError::Forbidden(_))
+            ));
+            failures += 1;
+        }
+
+        // Locked out: even the right password is rejected until the window has passed
+        assert!(matches!(
+            check_signing_password("wrong", &hash, failures),
+            Err(AppError::TooManyRequests(_))
+        ));
+        assert!(matches!(
+            check_signing_password("correct horse", &hash, failures),
+            Err(AppError::TooManyRequests(_))
+        ));
+    }
+}
@@ -0,0 +1,506 @@
+/// EMA Product Information Document Service
+///
+/// Optionally downloads the ePI / SmPC / PIL documents referenced by synced catalog
+/// entries into encrypted file storage, keeping a content hash (unchanged documents are
+/// not rewritten) and extracted text for full-text search.
+///
+/// Configuration:
+/// - `EMA_DOCUMENT_FETCH_ENABLED` (default false) - download documents during EMA syncs
+/// - `EMA_DOCUMENT_LANGUAGES` (default: `EMA_API_DEFAULT_LANGUAGE` or `en`) - comma-separated
+/// - `EMA_DOCUMENT_DELAY_MS` (default 500) - pause between document requests
+/// - `EMA_DOCUMENT_MAX_PER_SYNC` (default 200) - download cap per sync run
+/// - `EMA_DOCUMENT_MAX_BYTES` (default 20 MB) - larger documents are skipped
+
+use std::time::Duration;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::{
+    middleware::error_handling::{AppError, Result},
+    models::ema::*,
+    utils::EncryptedFileStorage,
+};
+
+/// A document to download for a catalog entry
+#[derive(Debug, Clone)]
+pub struct DocumentSource {
+    pub eu_number: String,
+    pub language_code: String,
+    pub document_type: EmaDocumentType,
+    pub url: String,
+}
+
+impl DocumentSource {
+    /// Document URLs present on a catalog entry
+    pub fn from_entry(entry: &EmaCatalogEntry) -> Vec<DocumentSource> {
+        let language_code = entry.language_code.clone().unwrap_or_else(|| "en".to_string());
+
+        [
+            (EmaDocumentType::Epi, &entry.epi_url),
+            (EmaDocumentType::Smpc, &entry.smpc_url),
+            (EmaDocumentType::Pil, &entry.pil_url),
+        ]
+        .into_iter()
+        .filter_map(|(document_type, url)| {
+            let url = url.as_deref()?.trim();
+            url.starts_with("https://").then(|| DocumentSource {
+                eu_number: entry.eu_number.clone(),
+                language_code: language_code.clone(),
+                document_type,
+                url: url.to_string(),
+            })
+        })
+        .collect()
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DocumentFetchSummary {
+    pub stored: i32,
+    pub unchanged: i32,
+    pub failed: i32,
+    pub skipped: i32,
+}
+
+pub struct EmaDocumentService {
+    db_pool: PgPool,
+    storage: EncryptedFileStorage,
+    http_client: reqwest::Client,
+    languages: Vec<String>,
+    request_delay_ms: u64,
+    max_per_sync: usize,
+    max_document_bytes: usize,
+}
+
+impl EmaDocumentService {
+    pub fn new(db_pool: PgPool, file_storage_path: &str, encryption_key: &str) -> Result<Self> {
+        let storage = EncryptedFileStorage::new(
+            std::path::Path::new(file_storage_path).join("ema_documents"),
+            encryption_key,
+        )?;
+
+        let http_client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(60))
+            .user_agent("Atlas-Pharma-EMA-Client/1.0")
+            .build()
+            .unwrap_or_default();
+
+        let languages = std::env::var("EMA_DOCUMENT_LANGUAGES")
+            .or_else(|_| std::env::var("EMA_API_DEFAULT_LANGUAGE"))
+            .unwrap_or_else(|_| "en".to_string())
+            .split(',')
+            .map(|l| l.trim().to_lowercase())
+            .filter(|l| !l.is_empty())
+            .collect();
+
+        Ok(Self {
+            db_pool,
+            storage,
+            http_client,
+            languages,
+            request_delay_ms: std::env::var("EMA_DOCUMENT_DELAY_MS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(500),
+            max_per_sync: std::env::var("EMA_DOCUMENT_MAX_PER_SYNC")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(200),
+            max_document_bytes: std::env::var("EMA_DOCUMENT_MAX_BYTES")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(20 * 1024 * 1024),
+        })
+    }
+
+    /// Build from `FILE_STORAGE_PATH` / `ENCRYPTION_KEY` (for background syncs without AppConfig)
+    pub fn from_env(db_pool: PgPool) -> Result<Self> {
+        let encryption_key = std::env::var("ENCRYPTION_KEY")
+            .map_err(|_| AppError::Internal(anyhow::anyhow!("ENCRYPTION_KEY not set")))?;
+        let file_storage_path = std::env::var("FILE_STORAGE_PATH").unwrap_or_else(|_| "./uploads".to_string());
+
+        Self::new(db_pool, &file_storage_path, &encryption_key)
+    }
+
+    /// Whether EMA syncs should download documents
+    pub fn fetch_enabled() -> bool {
+        std::env::var("EMA_DOCUMENT_FETCH_ENABLED")
+            .map(|v| v.eq_ignore_ascii_case("true") || v == "1")
+            .unwrap_or(false)
+    }
+
+    // ========================================================================
+    // DOWNLOAD
+    // ========================================================================
+
+    /// Download documents in the configured languages, up to the per-sync cap.
+    /// Stops early when EMA rate-limits us; individual failures are logged and counted.
+    pub async fn fetch_documents(&self, sources: &[DocumentSource]) -> DocumentFetchSummary {
+        let mut summary = DocumentFetchSummary::default();
+        let mut attempted = 0;
+
+        for source in sources {
+            if !self.languages.iter().any(|l| l == &source.language_code.to_lowercase()) {
+                summary.skipped += 1;
+                continue;
+            }
+            if attempted >= self.max_per_sync {
+                summary.skipped += 1;
+                continue;
+            }
+
+            if attempted > 0 {
+                tokio::time::sleep(Duration::from_millis(self.request_delay_ms)).await;
+            }
+            attempted += 1;
+
+            match self.fetch_document(source).await {
+                Ok(true) => summary.stored += 1,
+                Ok(false) => summary.unchanged += 1,
+                Err(AppError::TooManyRequests(msg)) => {
+                    tracing::warn!("EMA document downloads paused: {}", msg);
+                    summary.failed += 1;
+                    break;
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to fetch EMA {} document for {}: {:?}",
+                        source.document_type.as_str(), source.eu_number, e
+                    );
+                    summary.failed += 1;
+                }
+            }
+        }
+
+        summary
+    }
+
+    /// Download and store one document. Returns false when the content is unchanged.
+    async fn fetch_document(&self, source: &DocumentSource) -> Result<bool> {
+        let response = self
+            .http_client
+            .get(&source.url)
+            .header("Accept", "application/fhir+json,application/json,text/html,application/pdf;q=0.9,*/*;q=0.8")
+            .send()
+            .await
+            .map_err(|e| AppError::Internal(anyhow::anyhow!("Document request failed: {}", e)))?;
+
+        let status = response.status();
+        if status.as_u16() == 429 {
+            return Err(AppError::TooManyRequests("EMA rate limit exceeded".to_string()));
+        }
+        if !status.is_success() {
+            return Err(AppError::Internal(anyhow::anyhow!("EMA returned status {} for {}", status, source.url)));
+        }
+
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.to_string());
+        let body = response
+            .bytes()
+            .await
+            .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to read document body: {}", e)))?;
+
+        if body.len() > self.max_document_bytes {
+            return Err(AppError::BadRequest(format!(
+                "Document is {} bytes (limit {})",
+                body.len(), self.max_document_bytes
+            )));
+        }
+
+        let content_hash = format!("{:x}", Sha256::digest(&body));
+
+        let existing: Option<(Uuid, String)> = sqlx::query_as(
+            r#"
+            SELECT id, content_hash FROM ema_documents
+            WHERE eu_number = $1 AND language_code = $2 AND document_type = $3
+            "#
+        )
+        .bind(&source.eu_number)
+        .bind(&source.language_code)
+        .bind(source.document_type.as_str())
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        if let Some((id, hash)) = &existing {
+            if *hash == content_hash {
+                sqlx::query("UPDATE ema_documents SET fetched_at = NOW(), source_url = $2 WHERE id = $1")
+                    .bind(id)
+                    .bind(&source.url)
+                    .execute(&self.db_pool)
+                    .await?;
+                return Ok(false);
+            }
+        }
+
+        // One directory per document row; the file name is stable so updates overwrite it
+        let id = existing.map(|(id, _)| id).unwrap_or_else(Uuid::new_v4);
+        let (storage_path, _) = self.storage.save_encrypted_file(
+            id,
+            &format!("{}_{}", source.document_type.as_str(), source.language_code),
+            &body,
+        )?;
+        let extracted_text = extract_document_text(content_type.as_deref(), &body);
+
+        sqlx::query(
+            r#"
+            INSERT INTO ema_documents (
+                id, eu_number, language_code, document_type, source_url, storage_path,
+                content_hash, content_type, size_bytes, extracted_text
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            ON CONFLICT (eu_number, language_code, document_type) DO UPDATE SET
+                source_url = EXCLUDED.source_url,
+                storage_path = EXCLUDED.storage_path,
+                content_hash = EXCLUDED.content_hash,
+                content_type = EXCLUDED.content_type,
+                size_bytes = EXCLUDED.size_bytes,
+                extracted_text = EXCLUDED.extracted_text,
+                fetched_at = NOW(),
+                changed_at = NOW()
+            "#
+        )
+        .bind(id)
+        .bind(&source.eu_number)
+        .bind(&source.language_code)
+        .bind(source.document_type.as_str())
+        .bind(&source.url)
+        .bind(&storage_path)
+        .bind(&content_hash)
+        .bind(&content_type)
+        .bind(body.len() as i64)
+        .bind(&extracted_text)
+        .execute(&self.db_pool)
+        .await?;
+
+        tracing::info!(
+            "Stored EMA {} document for {} ({}, {} bytes)",
+            source.document_type.as_str(), source.eu_number, source.language_code, body.len()
+        );
+
+        Ok(true)
+    }
+
+    // ========================================================================
+    // LOOKUP
+    // ========================================================================
+
+    pub async fn list_documents(
+        &self,
+        eu_number: &str,
+        language: Option<&str>,
+        include_text: bool,
+    ) -> Result<Vec<EmaDocument>> {
+        let mut documents = sqlx::query_as::<_, EmaDocument>(
+            r#"
+            SELECT * FROM ema_documents
+            WHERE eu_number = $1
+              AND ($2::TEXT IS NULL OR language_code = $2)
+            ORDER BY language_code, document_type
+            "#
+        )
+        .bind(eu_number)
+        .bind(language)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        if !include_text {
+            for document in &mut documents {
+                document.extracted_text = None;
+            }
+        }
+
+        Ok(documents)
+    }
+
+    /// Full-text search over extracted document text
+    pub async fn search(&self, request: &EmaDocumentSearchRequest) -> Result<Vec<EmaDocumentSearchResult>> {
+        let query_text = request.q.trim();
+        if query_text.is_empty() {
+            return Err(AppError::BadRequest("Search query cannot be empty".to_string()));
+        }
+
+        let results = sqlx::query_as::<_, EmaDocumentSearchResult>(
+            r#"
+            SELECT d.eu_number, c.product_name, d.language_code, d.document_type,
+                   ts_headline('simple', coalesce(d.extracted_text, ''), plainto_tsquery('simple', $1),
+                               'MaxFragments=2, MaxWords=30, MinWords=10') AS excerpt,
+                   ts_rank(d.search_vector, plainto_tsquery('simple', $1)) AS rank
+            FROM ema_documents d
+            LEFT JOIN ema_catalog c ON c.eu_number = d.eu_number
+            WHERE d.search_vector @@ plainto_tsquery('simple', $1)
+              AND ($2::TEXT IS NULL OR d.language_code = $2)
+              AND ($3::TEXT IS NULL OR d.document_type = $3)
+            ORDER BY rank DESC, d.eu_number
+            LIMIT $4 OFFSET $5
+            "#
+        )
+        .bind(query_text)
+        .bind(request.language.as_deref())
+        .bind(request.document_type.map(|t| t.as_str()))
+        .bind(request.limit.unwrap_or(20).clamp(1, 100))
+        .bind(request.offset.unwrap_or(0).max(0))
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(results)
+    }
+}
+
+// ============================================================================
+// TEXT EXTRACTION
+// ============================================================================
+
+/// Extract searchable text from a downloaded document.
+/// Supports FHIR/JSON ePI bundles (section titles and XHTML narratives), HTML/XML and
+/// plain text; returns None for other formats such as PDF.
+pub fn extract_document_text(content_type: Option<&str>, body: &[u8]) -> Option<String> {
+    let content_type = content_type.unwrap_or("").to_lowercase();
+    let text = std::str::from_utf8(body).ok()?;
+    let trimmed = text.trim_start();
+
+    let extracted = if content_type.contains("json") || trimmed.starts_with('{') {
+        let value: serde_json::Value = serde_json::from_str(text).ok()?;
+        let mut parts = Vec::new();
+        collect_narrative(&value, &mut parts);
+        parts.join("\n")
+    } else if content_type.contains("html") || content_type.contains("xml") || trimmed.starts_with('<') {
+        strip_markup(text)
+    } else if content_type.starts_with("text/") {
+        text.to_string()
+    } else {
+        return None;
+    };
+
+    let normalized = normalize_whitespace(&extracted);
+    (!normalized.is_empty()).then_some(normalized)
+}
+
+/// Walk a FHIR resource tree collecting section titles and narrative (`text.div`) XHTML
+fn collect_narrative(value: &serde_json::Value, parts: &mut Vec<String>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            if let Some(title) = map.get("title").and_then(|t| t.as_str()) {
+                parts.push(title.to_string());
+            }
+            if let Some(div) = map.get("div").and_then(|d| d.as_str()) {
+                parts.push(strip_markup(div));
+            }
+            for (key, child) in map {
+                if key != "title" && key != "div" {
+                    collect_narrative(child, parts);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                collect_narrative(item, parts);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Drop tags (and script/style content), turning block boundaries into line breaks
+fn strip_markup(markup: &str) -> String {
+    let mut output = String::with_capacity(markup.len());
+    let mut rest = markup;
+
+    while let Some(start) = rest.find('<') {
+        output.push_str(&rest[..start]);
+        let after = &rest[start..];
+        let end = match after.find('>') {
+            Some(end) => end,
+            None => break,
+        };
+
+        let tag = after[1..end].trim_start_matches('/').to_lowercase();
+        let name: String = tag.chars().take_while(|c| c.is_ascii_alphanumeric()).collect();
+        rest = &after[end + 1..];
+
+        if !after.starts_with("</") && (name == "script" || name == "style") {
+            // Skip to the closing tag
+            let closing = format!("</{}", name);
+            rest = match rest.to_ascii_lowercase().find(&closing) {
+                Some(idx) => rest[idx..].find('>').map(|e| &rest[idx + e + 1..]).unwrap_or(""),
+                None => "",
+            };
+            continue;
+        }
+
+        if matches!(name.as_str(), "p" | "div" | "br" | "li" | "tr" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "table") {
+            output.push('\n');
+        } else {
+            output.push(' ');
+        }
+    }
+    if !rest.contains('<') {
+        output.push_str(rest);
+    }
+
+    decode_entities(&output)
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Collapse runs of spaces within lines and drop blank lines
+fn normalize_whitespace(text: &str) -> String {
+    text.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_fhir_narrative() {
+        let bundle = serde_json::json!({
+            "resourceType": "Bundle",
+            "entry": [{
+                "resource": {
+                    "resourceType": "Composition",
+                    "title": "Summary of Product Characteristics",
+                    "section": [{
+                        "title": "4.1 Therapeutic indications",
+                        "text": { "status": "additional", "div": "<div xmlns=\"http://www.w3.org/1999/xhtml\"><p>Treatment of type 2 diabetes &amp; obesity.</p></div>" }
+                    }]
+                }
+            }]
+        });
+
+        let text = extract_document_text(Some("application/fhir+json"), bundle.to_string().as_bytes()).unwrap();
+        assert!(text.contains("Summary of Product Characteristics"));
+        assert!(text.contains("4.1 Therapeutic indications"));
+        assert!(text.contains("Treatment of type 2 diabetes & obesity."));
+        assert!(!text.contains("<p>"));
+    }
+
+    #[test]
+    fn test_extract_html_skips_scripts() {
+        let html = b"<html><head><style>p { color: red }</style><script>alert(1)</script></head>\
+                     <body><h1>Package leaflet</h1><p>Read all of this leaflet&nbsp;carefully.</p></body></html>";
+
+        let text = extract_document_text(Some("text/html; charset=utf-8"), html).unwrap();
+        assert_eq!(text, "Package leaflet\nRead all of this leaflet carefully.");
+    }
+
+    #[test]
+    fn test_extract_unsupported_formats() {
+        assert_eq!(extract_document_text(Some("application/pdf"), b"%PDF-1.7 binary"), None);
+        assert_eq!(extract_document_text(None, &[0xff, 0xfe, 0x00]), None);
+    }
+}
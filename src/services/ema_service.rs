@@ -1,12 +1,29 @@
 use uuid::Uuid;
+use std::collections::HashMap;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use sqlx::{query, query_as, Row};
 use crate::models::ema::{
     EmaEpiApiResponse, EmaCatalogEntry, EmaCatalogResponse,
-    EmaSearchRequest, EmaSyncLog, EmaCatalogStats
+    EmaSearchRequest, EmaSyncLog, EmaSyncProgressResponse, EmaCatalogStats
 };
+use crate::models::background_job::{EmaSyncJob, JobType, NewJob};
+use crate::models::sync_preview::{SyncPreview, DEFAULT_PREVIEW_LIMIT, MAX_PREVIEW_LIMIT};
 use crate::repositories::ema_repo::EmaRepository;
-use crate::middleware::error_handling::{Result, AppError};
+use crate::models::sync_anomaly::SyncRunStats;
+use crate::models::search_engine::{FacetedSearchResponse, SearchIndex};
+use crate::services::search_engine::{self, SearchEngine};
+use crate::services::{BackgroundJobService, CatalogSource, CatalogSubscriptionService, DocumentSource, EmaDocumentService, QueryCache, QueryCacheKind, Shutdown, SyncAnomalyService, SyncLogTable, SyncSourceService};
+use crate::utils::RequestBudget;
+use crate::middleware::{error_handling::{Result, AppError}, metrics};
+
+/// Catalog fields compared by a dry-run sync (ids and sync timestamps always differ)
+const PREVIEW_FIELDS: &[&str] = &[
+    "product_name", "inn_name", "therapeutic_indication", "mah_name", "authorization_status",
+    "authorization_date", "procedure_type", "pharmaceutical_form", "route_of_administration",
+    "strength", "active_substances", "atc_code", "therapeutic_area", "orphan_designation",
+    "additional_monitoring", "epi_url", "smpc_url", "pil_url",
+];
 
 pub struct EmaService {
     repo: EmaRepository,
@@ -15,10 +32,22 @@ pub struct EmaService {
     default_sync_limit: usize,
     batch_delay_ms: u64,
     max_retries: usize,
+    requests_per_minute: u32,
+    burst_limit: u32,
+    request_budget: RequestBudget,
 }
 
 impl EmaService {
     pub fn new(repo: EmaRepository) -> Self {
+        let requests_per_minute = std::env::var("EMA_API_REQUESTS_PER_MINUTE")
+            .unwrap_or_else(|_| "60".to_string())
+            .parse()
+            .unwrap_or(60);
+        let burst_limit = std::env::var("EMA_API_BURST_LIMIT")
+            .unwrap_or_else(|_| "1".to_string())
+            .parse()
+            .unwrap_or(1);
+
         Self {
             repo,
             api_base_url: std::env::var("EMA_API_BASE_URL")
@@ -37,23 +66,42 @@ impl EmaService {
                 .unwrap_or_else(|_| "3".to_string())
                 .parse()
                 .unwrap_or(3),
+            requests_per_minute,
+            burst_limit,
+            request_budget: RequestBudget::with_burst(requests_per_minute, burst_limit),
+        }
+    }
+
+    /// A service for one sync run, paced by the admin-edited `ema` source settings
+    async fn with_runtime_settings(&self) -> Self {
+        let mut service = EmaService::new(EmaRepository::new(self.repo.pool.clone()));
+        match SyncSourceService::new(self.repo.pool.clone()).runtime_settings("ema").await {
+            Ok(settings) => {
+                service.requests_per_minute = settings.requests_per_minute.unwrap_or(service.requests_per_minute);
+                service.burst_limit = settings.burst_limit.unwrap_or(service.burst_limit);
+                service.request_budget = RequestBudget::with_burst(service.requests_per_minute, service.burst_limit);
+            }
+            Err(e) => tracing::warn!("Failed to load EMA source settings, using environment: {:?}", e),
         }
+        service
     }
 
     // ============================================================================
     // Public API Methods
     // ============================================================================
 
-    /// Sync data from EMA ePI API
+    /// Sync data from EMA ePI API (waits for the sync to finish)
     pub async fn sync_from_api(
         &self,
         language: Option<String>,
         limit: Option<usize>,
         sync_type: Option<String>
     ) -> Result<EmaSyncLog> {
-        let lang = language.unwrap_or_else(|| self.default_language.clone());
-        let sync_limit = limit.unwrap_or(self.default_sync_limit);
-        let sync_type_str = sync_type.unwrap_or_else(|| "full".to_string());
+        let (lang, sync_limit, sync_type_str) = self.sync_params(language, limit, sync_type);
+
+        if self.repo.is_sync_running().await? {
+            return Err(AppError::BadRequest("An EMA sync is already in progress".to_string()));
+        }
 
         let log_id = self.repo.start_sync_log(
             Some(lang.clone()),
@@ -61,12 +109,157 @@ impl EmaService {
             Some(sync_limit as i32)
         ).await?;
 
+        let service = self.with_runtime_settings().await;
+        service.run_sync(&lang, sync_limit, &sync_type_str, log_id).await
+    }
+
+    /// Queue a sync as a background job and return its sync log ID immediately.
+    /// Progress is available through `get_sync_progress`.
+    pub async fn start_background_sync(
+        &self,
+        language: Option<String>,
+        limit: Option<usize>,
+        sync_type: Option<String>
+    ) -> Result<Uuid> {
+        let (lang, sync_limit, sync_type_str) = self.sync_params(language, limit, sync_type);
+
+        if self.repo.is_sync_running().await? {
+            return Err(AppError::BadRequest("An EMA sync is already in progress".to_string()));
+        }
+
+        let log_id = self.repo.start_sync_log(
+            Some(lang.clone()),
+            Some(sync_type_str.clone()),
+            Some(sync_limit as i32)
+        ).await?;
+
+        let job = EmaSyncJob { sync_log_id: log_id, language: lang, limit: sync_limit, sync_type: sync_type_str };
+        let queued = BackgroundJobService::new(self.repo.pool.clone())
+            .enqueue(NewJob::new(JobType::EmaSync, &job).with_dedupe_key(format!("ema_sync:{}", log_id)))
+            .await;
+        if let Err(e) = queued {
+            let _ = self.repo.fail_sync_log(log_id, "Sync could not be queued", None).await;
+            return Err(e);
+        }
+
+        tracing::info!("EMA background sync queued with ID: {}", log_id);
+        Ok(log_id)
+    }
+
+    /// Run the sync of a background job; a retry starts the sync over. Failures are
+    /// recorded on the sync log by run_sync.
+    pub async fn run_sync_job(job: &EmaSyncJob, retry: bool, pool: sqlx::PgPool) -> Result<()> {
+        let repo = EmaRepository::new(pool);
+
+        // The previous attempt failed the sync log or left it in progress when its worker died
+        if retry && !repo.restart_sync_log(job.sync_log_id).await? {
+            tracing::info!("EMA sync {} was cancelled or has finished, not retrying it", job.sync_log_id);
+            return Ok(());
+        }
+
+        let service = EmaService::new(repo).with_runtime_settings().await;
+        service.run_sync(&job.language, job.limit, &job.sync_type, job.sync_log_id).await?;
+        Ok(())
+    }
+
+    /// Dry run: fetch up to `limit` entries and report what a sync would change, without writing
+    pub async fn preview_sync(&self, language: Option<String>, limit: Option<usize>) -> Result<SyncPreview> {
+        let language = language.unwrap_or_else(|| self.default_language.clone());
+        let limit = limit.unwrap_or(DEFAULT_PREVIEW_LIMIT).clamp(1, MAX_PREVIEW_LIMIT);
+        let mut preview = SyncPreview::new("ema");
+
+        let url = format!(
+            "{}/ListBySearchParameter?_format=json&language={}",
+            self.api_base_url, language
+        );
+        let (api_response, _) = self.fetch_from_ema_api_with_retry(&url).await?;
+
+        let mut entries = Vec::new();
+        for epi_entry in api_response.entry.unwrap_or_default().into_iter().take(limit) {
+            preview.records_fetched += 1;
+            match epi_entry.to_catalog_entry() {
+                Ok(entry) if entry.language_code.as_deref().is_some_and(|l| l != language) => {}
+                Ok(entry) => entries.push(entry),
+                Err(e) => {
+                    tracing::debug!("Dry run: failed to convert EPI entry: {}", e);
+                    preview.invalid += 1;
+                }
+            }
+        }
+
+        let eu_numbers: Vec<String> = entries.iter().map(|e| e.eu_number.clone()).collect();
+        let existing: HashMap<String, serde_json::Value> = self
+            .repo
+            .find_by_eu_numbers(&eu_numbers)
+            .await?
+            .into_iter()
+            .map(|e| (e.eu_number.clone(), serde_json::to_value(&e).unwrap_or_default()))
+            .collect();
+
+        for entry in &entries {
+            let incoming = serde_json::to_value(entry).unwrap_or_default();
+            preview.record(
+                &entry.eu_number,
+                &entry.product_name,
+                existing.get(&entry.eu_number),
+                &incoming,
+                PREVIEW_FIELDS,
+            );
+        }
+
+        tracing::info!(
+            "EMA dry run ({}): fetched={}, would_insert={}, would_update={}, unchanged={}, invalid={}",
+            language, preview.records_fetched, preview.would_insert, preview.would_update,
+            preview.unchanged, preview.invalid
+        );
+
+        Ok(preview)
+    }
+
+    /// Request cancellation of a running sync; it stops at its next batch
+    pub async fn cancel_sync(&self, sync_id: Uuid, user_id: Uuid) -> Result<bool> {
+        self.repo.cancel_sync(sync_id, user_id).await
+    }
+
+    /// Get sync progress by ID
+    pub async fn get_sync_progress(&self, sync_id: Uuid) -> Result<Option<EmaSyncProgressResponse>> {
+        Ok(self.repo.get_sync_log(sync_id).await?.map(Into::into))
+    }
+
+    /// Get the current active sync progress
+    pub async fn get_active_sync(&self) -> Result<Option<EmaSyncProgressResponse>> {
+        Ok(self.repo.get_active_sync().await?.map(Into::into))
+    }
+
+    /// Check if a sync is currently running
+    pub async fn is_sync_running(&self) -> Result<bool> {
+        self.repo.is_sync_running().await
+    }
+
+    fn sync_params(
+        &self,
+        language: Option<String>,
+        limit: Option<usize>,
+        sync_type: Option<String>
+    ) -> (String, usize, String) {
+        (
+            language.unwrap_or_else(|| self.default_language.clone()),
+            limit.unwrap_or(self.default_sync_limit),
+            sync_type.unwrap_or_else(|| "full".to_string()),
+        )
+    }
+
+    /// Run a sync for an existing sync log and record the outcome on it
+    async fn run_sync(&self, lang: &str, sync_limit: usize, sync_type: &str, log_id: Uuid) -> Result<EmaSyncLog> {
+        let _in_flight = Shutdown::track_sync(SyncLogTable::Ema, log_id);
         let sync_start_time = Instant::now();
+        let catalog_size = self.repo.get_total_count().await?;
 
-        match self.perform_sync(&lang, sync_limit, &sync_type_str, log_id).await {
+        match self.perform_sync(lang, sync_limit, sync_type, log_id).await {
             Ok((fetched, inserted, updated, skipped, failed, api_response_time_ms)) => {
                 let processing_time_ms = sync_start_time.elapsed().as_millis() as i32;
 
+                // No-op when the sync was cancelled meanwhile
                 self.repo.complete_sync_log(
                     log_id,
                     fetched,
@@ -77,6 +270,26 @@ impl EmaService {
                     Some(api_response_time_ms),
                     Some(processing_time_ms),
                 ).await?;
+                QueryCache::invalidate_source(CatalogSource::Ema);
+                metrics::record_sync_run("ema", fetched, inserted, updated, failed, sync_start_time.elapsed());
+
+                // Guardrails: suspect runs don't count as a successful refresh
+                let guard = SyncAnomalyService::new(self.repo.pool.clone());
+                let anomalies = guard.assess(&SyncRunStats {
+                    catalog_size,
+                    fetched: fetched as i64,
+                    inserted: inserted as i64,
+                    updated: updated as i64,
+                    failed: failed as i64,
+                    removed: 0,
+                    altered: guard.count_catalog_changes(log_id).await?,
+                    incremental: sync_type == "incremental",
+                });
+                if anomalies.is_empty()
+                    || self.repo.mark_sync_suspect(log_id, &serde_json::to_value(&anomalies)?).await?
+                {
+                    guard.report("ema", log_id, &anomalies).await;
+                }
 
                 // Retrieve and return the completed sync log
                 let sync_log = query_as::<_, EmaSyncLog>(
@@ -88,6 +301,11 @@ impl EmaService {
 
                 Ok(sync_log)
             }
+            Err(e) if Shutdown::requested() => {
+                tracing::warn!("EMA sync {} interrupted by shutdown: {:?}", log_id, e);
+                SyncLogTable::Ema.mark_interrupted(&self.repo.pool, log_id).await?;
+                Err(e)
+            }
             Err(e) => {
                 let error_msg = format!("EMA sync failed: {:?}", e);
                 tracing::error!("EMA sync failed for language {}: {}", lang, error_msg);
@@ -99,16 +317,38 @@ impl EmaService {
     }
 
     /// Search catalog with filters
-    pub async fn search(&self, request: EmaSearchRequest) -> Result<Vec<EmaCatalogResponse>> {
-        let entries = self.repo.search(&request).await?;
-        let responses = entries.into_iter().map(Into::into).collect();
-        Ok(responses)
+    pub async fn search(
+        &self,
+        request: EmaSearchRequest,
+        engine: Option<&Arc<dyn SearchEngine>>,
+    ) -> Result<FacetedSearchResponse<EmaCatalogResponse>> {
+        let query = request.engine_query()?;
+
+        match (search_engine::search_with_fallback(engine, SearchIndex::Ema, query.as_ref()).await, engine) {
+            (Some(hits), Some(engine)) => {
+                let entries = self.repo.find_by_eu_numbers(&hits.keys).await?;
+                let entries = search_engine::in_hit_order(entries, &hits.keys, |e| e.eu_number.clone());
+                Ok(FacetedSearchResponse {
+                    results: entries.into_iter().map(Into::into).collect(),
+                    facets: hits.facets,
+                    facets_as_of: None,
+                    total: Some(hits.total),
+                    engine: engine.name().to_string(),
+                })
+            }
+            _ => {
+                let entries = self.repo.search(&request).await?;
+                Ok(FacetedSearchResponse::from_postgres(entries.into_iter().map(Into::into).collect()))
+            }
+        }
     }
 
-    /// Get medicine by EU number
+    /// Get medicine by EU number (cached until the next catalog sync)
     pub async fn get_by_eu_number(&self, eu_number: &str) -> Result<Option<EmaCatalogResponse>> {
-        let entry = self.repo.find_by_eu_number(eu_number).await?;
-        Ok(entry.map(Into::into))
+        QueryCache::get_or_load(QueryCacheKind::EmaEuNumber, eu_number.trim(), || async {
+            let entry = self.repo.find_by_eu_number(eu_number).await?;
+            Ok(entry.map(Into::into))
+        }).await
     }
 
     /// Get catalog statistics
@@ -160,9 +400,9 @@ impl EmaService {
         let mut total_api_response_time = 0;
 
         match sync_type {
-            "full" => {
+            "full" | "scheduled" => {
                 let (fetched, inserted, updated, skipped, failed, api_time) =
-                    self.perform_full_sync(language, limit).await?;
+                    self.perform_full_sync(language, limit, log_id).await?;
                 total_fetched = fetched;
                 total_inserted = inserted;
                 total_updated = updated;
@@ -175,7 +415,7 @@ impl EmaService {
                 // For now, fall back to full sync
                 tracing::warn!("Incremental sync not yet implemented, falling back to full sync");
                 let (fetched, inserted, updated, skipped, failed, api_time) =
-                    self.perform_full_sync(language, limit).await?;
+                    self.perform_full_sync(language, limit, log_id).await?;
                 total_fetched = fetched;
                 total_inserted = inserted;
                 total_updated = updated;
@@ -185,7 +425,7 @@ impl EmaService {
             }
             "by_language" => {
                 let (fetched, inserted, updated, skipped, failed, api_time) =
-                    self.perform_language_sync(language, limit).await?;
+                    self.perform_language_sync(language, limit, log_id).await?;
                 total_fetched = fetched;
                 total_inserted = inserted;
                 total_updated = updated;
@@ -198,6 +438,11 @@ impl EmaService {
             }
         }
 
+        let subscriptions = CatalogSubscriptionService::new(self.repo.pool.clone());
+        if let Err(e) = subscriptions.dispatch_pending_events().await {
+            tracing::error!("Catalog subscription dispatch failed: {:?}", e);
+        }
+
         tracing::info!(
             "EMA sync completed: fetched={}, inserted={}, updated={}, skipped={}, failed={}, avg_api_time_ms={}",
             total_fetched, total_inserted, total_updated, total_skipped, total_failed,
@@ -211,7 +456,8 @@ impl EmaService {
     async fn perform_full_sync(
         &self,
         language: &str,
-        limit: usize
+        limit: usize,
+        log_id: Uuid
     ) -> Result<(i32, i32, i32, i32, i32, i32)> {
         let mut total_fetched = 0;
         let mut total_inserted = 0;
@@ -219,12 +465,27 @@ impl EmaService {
         let mut total_skipped = 0;
         let mut total_failed = 0;
         let mut total_api_time = 0;
+        let subscriptions = CatalogSubscriptionService::new(self.repo.pool.clone());
+        let fetch_documents = EmaDocumentService::fetch_enabled();
+        let mut document_sources: Vec<DocumentSource> = Vec::new();
 
         // EMA ePI API uses ListBySearchParameter endpoint
         let mut offset = 0;
         const BATCH_SIZE: usize = 50; // Process in smaller batches
+        let mut batch = 0;
+        let total_batches = limit.div_ceil(BATCH_SIZE) as i32;
+        self.repo.set_total_expected(log_id, limit as i32, total_batches).await?;
 
         while total_fetched < limit as i32 {
+            if self.repo.is_sync_cancelled(log_id).await? {
+                tracing::info!("EMA sync {} cancelled, stopping after {} records", log_id, total_fetched);
+                break;
+            }
+            if Shutdown::requested() {
+                tracing::info!("EMA sync {} stopping for shutdown after {} records", log_id, total_fetched);
+                return Err(Shutdown::interrupted());
+            }
+
             let batch_start = Instant::now();
             let current_limit = std::cmp::min(BATCH_SIZE, limit - total_fetched as usize);
 
@@ -236,6 +497,14 @@ impl EmaService {
             let (api_response, response_time) = self.fetch_from_ema_api_with_retry(&url).await?;
             total_api_time += response_time;
 
+            if batch == 0 {
+                if let Some(total) = api_response.total.filter(|t| *t > 0 && (*t as usize) < limit) {
+                    self.repo
+                        .set_total_expected(log_id, total, (total as usize).div_ceil(BATCH_SIZE) as i32)
+                        .await?;
+                }
+            }
+
             if api_response.entry.is_none() || api_response.entry.as_ref().unwrap().is_empty() {
                 tracing::info!("No more entries from EMA API, ending sync");
                 break;
@@ -272,16 +541,31 @@ impl EmaService {
                 break;
             }
 
+            // Diff subscribed records before they are overwritten
+            if let Err(e) = subscriptions.capture_ema_changes(&entries, Some(log_id)).await {
+                tracing::warn!("Catalog change capture failed: {:?}", e);
+            }
+
+            if fetch_documents {
+                document_sources.extend(entries.iter().flat_map(DocumentSource::from_entry));
+            }
+
             // Batch upsert to database
             let (inserted, updated) = self.repo.batch_upsert(entries.clone()).await?;
             total_inserted += inserted;
             total_updated += updated;
             total_fetched += entries.len() as i32;
+            batch += 1;
+
+            self.repo.update_sync_progress(
+                log_id, total_fetched, total_inserted, total_updated, total_skipped, total_failed, batch
+            ).await?;
 
             tracing::debug!(
                 "Processed batch of {} entries (inserted: {}, updated: {}) in {}ms",
                 entries.len(), inserted, updated, batch_start.elapsed().as_millis()
             );
+            metrics::record_sync_batch("ema", batch_start.elapsed());
 
             // Rate limiting - delay between batches
             if total_fetched < limit as i32 {
@@ -289,6 +573,20 @@ impl EmaService {
             }
         }
 
+        // Document downloads are best-effort and never fail the catalog sync
+        if !document_sources.is_empty() && !self.repo.is_sync_cancelled(log_id).await? {
+            match EmaDocumentService::from_env(self.repo.pool.clone()) {
+                Ok(documents) => {
+                    let summary = documents.fetch_documents(&document_sources).await;
+                    tracing::info!(
+                        "EMA documents: stored={}, unchanged={}, failed={}, skipped={}",
+                        summary.stored, summary.unchanged, summary.failed, summary.skipped
+                    );
+                }
+                Err(e) => tracing::warn!("EMA document storage unavailable: {:?}", e),
+            }
+        }
+
         Ok((total_fetched, total_inserted, total_updated, total_skipped, total_failed, total_api_time))
     }
 
@@ -296,10 +594,11 @@ impl EmaService {
     async fn perform_language_sync(
         &self,
         language: &str,
-        limit: usize
+        limit: usize,
+        log_id: Uuid
     ) -> Result<(i32, i32, i32, i32, i32, i32)> {
         // For now, language sync is the same as full sync but filtered by language
-        self.perform_full_sync(language, limit).await
+        self.perform_full_sync(language, limit, log_id).await
     }
 
     /// Fetch data from EMA API with retry logic
@@ -310,6 +609,7 @@ impl EmaService {
         let mut last_error = None;
 
         for attempt in 1..=self.max_retries {
+            self.request_budget.acquire().await;
             let start_time = Instant::now();
 
             match self.fetch_from_ema_api(url).await {
@@ -348,10 +648,14 @@ impl EmaService {
             .header("Accept", "application/fhir+json,application/json")
             .send()
             .await
-            .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to send request to EMA API: {}", e)))?;
+            .map_err(|e| {
+                metrics::record_sync_api_error("ema", "network");
+                AppError::Internal(anyhow::anyhow!("Failed to send request to EMA API: {}", e))
+            })?;
 
         let status = response.status();
         if !status.is_success() {
+            metrics::record_sync_api_error("ema", status.as_str());
             let error_text = response
                 .text()
                 .await
@@ -365,7 +669,10 @@ impl EmaService {
         let api_response: EmaEpiApiResponse = response
             .json()
             .await
-            .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to parse EMA API response: {}", e)))?;
+            .map_err(|e| {
+                metrics::record_sync_api_error("ema", "parse");
+                AppError::Internal(anyhow::anyhow!("Failed to parse EMA API response: {}", e))
+            })?;
 
         // Validate response structure
         if api_response.resource_type != "Bundle" {
@@ -440,4 +747,127 @@ impl EmaService {
             "supported_languages": self.get_supported_languages()
         })
     }
-}
\ No newline at end of file
+}
+
+// ============================================================================
+// SCHEDULER
+// ============================================================================
+
+/// Background EMA sync on a fixed interval, mirroring `OpenFdaSyncScheduler`
+///
+/// Configuration:
+/// - `EMA_SYNC_INTERVAL_HOURS` (default 168, weekly)
+/// - `EMA_SYNC_REFRESH_DAYS` (default 7) - skip runs while the catalog is fresher than this
+/// - `EMA_SYNC_LANGUAGES` (default: `EMA_API_DEFAULT_LANGUAGE`) - comma-separated, synced in turn
+pub struct EmaSyncScheduler {
+    pool: sqlx::PgPool,
+    interval_hours: u64,
+    refresh_days: i64,
+    languages: Vec<String>,
+}
+
+impl EmaSyncScheduler {
+    pub fn new(pool: sqlx::PgPool) -> Self {
+        let interval_hours = std::env::var("EMA_SYNC_INTERVAL_HOURS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(168); // Default: weekly (168 hours)
+
+        let refresh_days = std::env::var("EMA_SYNC_REFRESH_DAYS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(7);
+
+        let languages = std::env::var("EMA_SYNC_LANGUAGES")
+            .or_else(|_| std::env::var("EMA_API_DEFAULT_LANGUAGE"))
+            .unwrap_or_else(|_| "en".to_string())
+            .split(',')
+            .map(|l| l.trim().to_lowercase())
+            .filter(|l| !l.is_empty())
+            .collect();
+
+        Self { pool, interval_hours, refresh_days, languages }
+    }
+
+    pub fn with_interval(pool: sqlx::PgPool, interval_hours: u64) -> Self {
+        Self { interval_hours, ..Self::new(pool) }
+    }
+
+    /// Run the scheduler loop
+    pub async fn run(&self) {
+        let interval = Duration::from_secs(self.interval_hours * 3600);
+        let mut ticker = tokio::time::interval(interval);
+
+        // Skip first tick (runs immediately on start)
+        ticker.tick().await;
+
+        tracing::info!(
+            "EMA sync scheduler started - syncing {:?} every {} hours",
+            self.languages, self.interval_hours
+        );
+
+        loop {
+            let deadline = ticker.tick().await;
+            metrics::record_scheduler_lag("ema", deadline);
+            if crate::services::MaintenanceService::pauses("ema")
+                || !crate::services::SchedulerLeader::leads("ema").await
+            {
+                continue;
+            }
+            self.run_scheduled_sync().await;
+        }
+    }
+
+    /// Run a single scheduled sync (languages one after another)
+    pub async fn run_scheduled_sync(&self) {
+        if SyncSourceService::new(self.pool.clone()).is_scheduler_paused("ema").await {
+            tracing::info!("EMA sync scheduler is paused by an admin, skipping scheduled sync");
+            return;
+        }
+
+        tracing::info!("Running scheduled EMA sync...");
+
+        let service = EmaService::new(EmaRepository::new(self.pool.clone()));
+
+        // Check if sync is needed
+        match service.needs_refresh(Some(self.refresh_days)).await {
+            Ok(false) => {
+                tracing::info!("EMA catalog is up to date, skipping scheduled sync");
+                return;
+            }
+            Ok(true) => {}
+            Err(e) => {
+                tracing::error!("Failed to check if EMA refresh needed: {:?}", e);
+                return;
+            }
+        }
+
+        for language in &self.languages {
+            if let Err(e) = service.validate_language(language) {
+                tracing::warn!("Skipping scheduled EMA sync for '{}': {:?}", language, e);
+                continue;
+            }
+
+            // Check if sync is already running (e.g. one triggered manually)
+            match service.is_sync_running().await {
+                Ok(true) => {
+                    tracing::info!("EMA sync already in progress, skipping scheduled sync");
+                    return;
+                }
+                Ok(false) => {}
+                Err(e) => {
+                    tracing::error!("Failed to check EMA sync status: {:?}", e);
+                    return;
+                }
+            }
+
+            match service.sync_from_api(Some(language.clone()), None, Some("scheduled".to_string())).await {
+                Ok(log) => tracing::info!(
+                    "Scheduled EMA sync for '{}' finished with status {} ({} records)",
+                    language, log.status, log.records_fetched.unwrap_or(0)
+                ),
+                Err(e) => tracing::error!("Scheduled EMA sync for '{}' failed: {:?}", language, e),
+            }
+        }
+    }
+}
@@ -0,0 +1,93 @@
+/// Email Service
+///
+/// Outbound email over SMTP (STARTTLS) for report delivery. Configured with
+/// `SMTP_HOST`, `SMTP_PORT`, `SMTP_USERNAME`, `SMTP_PASSWORD` and `EMAIL_FROM`; when
+/// `SMTP_HOST` is unset email is disabled and callers fall back to in-app notifications.
+
+use lettre::{
+    message::{header::ContentType, Attachment, Mailbox, MultiPart, SinglePart},
+    transport::smtp::authentication::Credentials,
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+use crate::middleware::error_handling::{AppError, Result};
+
+/// A file attached to an email
+pub struct EmailAttachment {
+    pub filename: String,
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+pub struct EmailService {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl EmailService {
+    /// `None` when SMTP is not configured
+    pub fn from_env() -> Result<Option<Self>> {
+        let host = match std::env::var("SMTP_HOST").ok().filter(|h| !h.trim().is_empty()) {
+            Some(host) => host,
+            None => return Ok(None),
+        };
+
+        let from = std::env::var("EMAIL_FROM")
+            .unwrap_or_else(|_| "Atlas Pharma <reports@atlaspharma.local>".to_string())
+            .parse::<Mailbox>()
+            .map_err(|e| AppError::Internal(anyhow::anyhow!("Invalid EMAIL_FROM: {}", e)))?;
+
+        let mut builder = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&host)
+            .map_err(|e| AppError::Internal(anyhow::anyhow!("Invalid SMTP_HOST: {}", e)))?;
+
+        if let Some(port) = std::env::var("SMTP_PORT").ok().and_then(|p| p.parse().ok()) {
+            builder = builder.port(port);
+        }
+        if let (Ok(username), Ok(password)) = (std::env::var("SMTP_USERNAME"), std::env::var("SMTP_PASSWORD")) {
+            builder = builder.credentials(Credentials::new(username, password));
+        }
+
+        Ok(Some(Self {
+            transport: builder.build(),
+            from,
+        }))
+    }
+
+    /// Send a plain-text email, optionally with one attachment
+    pub async fn send(
+        &self,
+        to: &str,
+        subject: &str,
+        body: &str,
+        attachment: Option<EmailAttachment>,
+    ) -> Result<()> {
+        let to = to
+            .parse::<Mailbox>()
+            .map_err(|e| AppError::BadRequest(format!("Invalid recipient address: {}", e)))?;
+
+        let builder = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(subject);
+
+        let message = match attachment {
+            None => builder.header(ContentType::TEXT_PLAIN).body(body.to_string()),
+            Some(file) => {
+                let content_type = ContentType::parse(&file.content_type)
+                    .map_err(|e| AppError::Internal(anyhow::anyhow!("Invalid attachment type: {}", e)))?;
+                builder.multipart(
+                    MultiPart::mixed()
+                        .singlepart(SinglePart::plain(body.to_string()))
+                        .singlepart(Attachment::new(file.filename).body(file.data, content_type)),
+                )
+            }
+        }
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to build email: {}", e)))?;
+
+        self.transport
+            .send(message)
+            .await
+            .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to send email: {}", e)))?;
+
+        Ok(())
+    }
+}
@@ -40,10 +40,11 @@
 //
 // 1. Generate new DEK
 // 2. Encrypt new DEK with current Master Key
-// 3. Store encrypted DEK in database
+// 3. Store encrypted DEK in database, activating after ENCRYPTION_KEY_ACTIVATION_DELAY_SECONDS
 // 4. Mark old DEK as deprecated (keep for decryption)
-// 5. Background job re-encrypts data with new DEK
-// 6. Delete old DEK after re-encryption complete
+// 5. Every instance loads the new DEK into its keyring before it activates
+// 6. Background job (encryption_reencrypt) re-encrypts data with new DEK
+// 7. Old DEK marked rotated once no data uses it (kept for restored backups)
 //
 // ## Future: KMS Integration
 //
@@ -59,9 +60,16 @@ use sqlx::PgPool;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 use crate::services::EncryptionService;
+use crate::services::encryption_service::DataKey;
 use crate::middleware::error_handling::{Result, AppError};
 
+/// How often every instance reloads its keyring
+pub const KEYRING_REFRESH_SECONDS: u64 = 30;
+
+const DEFAULT_ACTIVATION_DELAY_SECONDS: u64 = 120;
+
 /// Key rotation status
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "key_status", rename_all = "lowercase")]
@@ -187,21 +195,22 @@ impl EncryptionKeyRotationService {
         let dek = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, dek_bytes);
 
         // Encrypt DEK with Master Key
-        let master_encryption = EncryptionService::new(&self.master_key)?;
+        let master_encryption = EncryptionService::master(&self.master_key)?;
         let encrypted_dek = master_encryption.encrypt(&dek)?;
 
         // Store in database
         let key = sqlx::query_as!(
             DataEncryptionKey,
             r#"
-            INSERT INTO data_encryption_keys (key_version, encrypted_key, status)
-            VALUES (1, $1, 'active')
+            INSERT INTO data_encryption_keys (key_version, encrypted_key, status, activates_at)
+            VALUES (1, $1, 'active', NOW() + make_interval(secs => $2))
             RETURNING id, key_version, encrypted_key,
                       status as "status: KeyStatus",
                       is_active, created_at, valid_until,
                       deprecated_at, rotated_at, rotated_by, rotation_reason
             "#,
-            encrypted_dek
+            encrypted_dek,
+            Self::activation_delay().as_secs_f64()
         )
         .fetch_one(&self.db_pool)
         .await?;
@@ -219,7 +228,7 @@ impl EncryptionKeyRotationService {
     /// Returns the plaintext DEK for use in data encryption/decryption
     ///
     pub fn decrypt_dek(&self, encrypted_dek: &str) -> Result<String> {
-        let master_encryption = EncryptionService::new(&self.master_key)?;
+        let master_encryption = EncryptionService::master(&self.master_key)?;
         master_encryption.decrypt(encrypted_dek)
             .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to decrypt DEK: {}", e)))
     }
@@ -236,7 +245,8 @@ impl EncryptionKeyRotationService {
     /// 5. Mark old DEK as deprecated
     /// 6. Return new key for immediate use
     ///
-    /// **Note:** Old key kept for decrypting existing data
+    /// **Note:** Old key kept for decrypting existing data, and stays the key
+    /// new data is encrypted with until the new one activates (see `activation_delay`)
     /// Background job should re-encrypt data with new key
     ///
     pub async fn rotate_key(&self) -> Result<DataEncryptionKey> {
@@ -250,7 +260,7 @@ impl EncryptionKeyRotationService {
         let dek = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, dek_bytes);
 
         // Encrypt new DEK with Master Key
-        let master_encryption = EncryptionService::new(&self.master_key)?;
+        let master_encryption = EncryptionService::master(&self.master_key)?;
         let encrypted_dek = master_encryption.encrypt(&dek)?;
 
         // Start transaction
@@ -272,15 +282,16 @@ impl EncryptionKeyRotationService {
         let new_key = sqlx::query_as!(
             DataEncryptionKey,
             r#"
-            INSERT INTO data_encryption_keys (key_version, encrypted_key, status)
-            VALUES ($1, $2, 'active')
+            INSERT INTO data_encryption_keys (key_version, encrypted_key, status, activates_at)
+            VALUES ($1, $2, 'active', NOW() + make_interval(secs => $3))
             RETURNING id, key_version, encrypted_key,
                       status as "status: KeyStatus",
                       is_active, created_at, valid_until,
                       deprecated_at, rotated_at, rotated_by, rotation_reason
             "#,
             current_key.key_version + 1,
-            encrypted_dek
+            encrypted_dek,
+            Self::activation_delay().as_secs_f64()
         )
         .fetch_one(&mut *tx)
         .await?;
@@ -361,6 +372,57 @@ impl EncryptionKeyRotationService {
         Ok(())
     }
 
+    /// Time between storing a new key and encrypting with it, so every instance has
+    /// loaded it by then (ENCRYPTION_KEY_ACTIVATION_DELAY_SECONDS, default 120)
+    pub fn activation_delay() -> Duration {
+        let seconds = std::env::var("ENCRYPTION_KEY_ACTIVATION_DELAY_SECONDS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|seconds| *seconds >= 2 * KEYRING_REFRESH_SECONDS)
+            .unwrap_or(DEFAULT_ACTIVATION_DELAY_SECONDS);
+        Duration::from_secs(seconds)
+    }
+
+    /// Load every DEK into the keyring of `EncryptionService`
+    ///
+    /// Returns the version of the key new data is encrypted with (0 while none is active)
+    pub async fn load_keyring(&self) -> Result<i32> {
+        let rows: Vec<(i32, String, DateTime<Utc>)> = sqlx::query_as(
+            "SELECT key_version, encrypted_key, activates_at FROM data_encryption_keys ORDER BY key_version",
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let keys = rows
+            .into_iter()
+            .map(|(version, encrypted_key, activates_at)| {
+                Ok(DataKey { version, key: self.decrypt_dek(&encrypted_key)?, activates_at })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        EncryptionService::install_keyring(&self.master_key, keys)?;
+        Ok(EncryptionService::new(&self.master_key)?.active_key_version())
+    }
+
+    /// Reload the keyring every KEYRING_REFRESH_SECONDS, so keys rotated on another
+    /// instance are known here before they activate
+    pub async fn run_keyring_refresh(&self) {
+        let mut ticker = tokio::time::interval(Duration::from_secs(KEYRING_REFRESH_SECONDS));
+        let mut active_version = None;
+
+        loop {
+            ticker.tick().await;
+            match self.load_keyring().await {
+                Ok(version) if active_version != Some(version) => {
+                    tracing::info!("🔑 Encrypting new data with key version {}", version);
+                    active_version = Some(version);
+                }
+                Ok(_) => {}
+                Err(e) => tracing::error!("Failed to reload the encryption keyring: {}", e),
+            }
+        }
+    }
+
     /// Get rotation schedule recommendation
     ///
     /// Returns days until next recommended rotation
@@ -0,0 +1,455 @@
+/// Encryption Re-encryption Service
+///
+/// After a key rotation, existing ciphertext keeps the key version it was written with
+/// (see `EncryptionService`). The `encryption_reencrypt` background job moves every
+/// encrypted column of ENCRYPTED_TABLES to the active key without taking anything offline:
+/// - rows still on an older key are read in primary key order, ENCRYPTION_REENCRYPT_BATCH_SIZE
+///   (default 200) at a time, pausing ENCRYPTION_REENCRYPT_PAUSE_MS (default 250) between
+///   batches so the job doesn't compete with request traffic
+/// - a value is only replaced while it is unchanged since it was read, so a concurrent
+///   write (already on the active key) wins
+/// - rows on the active key are never read again, so a retried job continues with
+///   what is left
+/// - once no table has rows on an older key, the deprecated keys are marked rotated
+///
+/// `progress` reports per table the rows holding ciphertext and how many of them are
+/// on the active key, for the admin encryption dashboard.
+
+use serde::Serialize;
+use utoipa::ToSchema;
+use sqlx::{postgres::PgRow, PgPool, Row};
+use std::collections::BTreeMap;
+use std::time::Duration;
+use crate::{
+    middleware::error_handling::{AppError, Result},
+    services::{
+        encryption_key_rotation_service::{EncryptionKeyRotationService, KeyStatus},
+        EncryptionService, Shutdown,
+    },
+};
+
+const DEFAULT_BATCH_SIZE: i64 = 200;
+const DEFAULT_PAUSE_MS: u64 = 250;
+
+/// A table with columns encrypted by `EncryptionService`
+struct EncryptedTable {
+    table: &'static str,
+    key: &'static str,
+    /// SQL type of `key`, for keyset pagination
+    key_type: &'static str,
+    columns: &'static [&'static str],
+    /// TEXT[] columns whose elements are encrypted one by one
+    array_columns: &'static [&'static str],
+}
+
+/// Every table holding text encrypted by `EncryptionService`. Ed25519 private keys have
+/// their own cipher, and encrypted files always use ENCRYPTION_KEY itself.
+const ENCRYPTED_TABLES: &[EncryptedTable] = &[
+    EncryptedTable {
+        table: "users",
+        key: "id",
+        key_type: "uuid",
+        columns: &[
+            "email_encrypted",
+            "contact_person_encrypted",
+            "phone_encrypted",
+            "address_encrypted",
+            "license_number_encrypted",
+            "mfa_secret_encrypted",
+        ],
+        array_columns: &["mfa_backup_codes_encrypted"],
+    },
+    EncryptedTable {
+        table: "accounting_connections",
+        key: "id",
+        key_type: "uuid",
+        columns: &["client_id", "client_secret", "access_token", "refresh_token"],
+        array_columns: &[],
+    },
+    EncryptedTable {
+        table: "alert_channels",
+        key: "id",
+        key_type: "uuid",
+        columns: &["webhook_url", "client_id", "client_secret", "access_token"],
+        array_columns: &[],
+    },
+    EncryptedTable {
+        table: "catalog_subscriptions",
+        key: "id",
+        key_type: "uuid",
+        columns: &["webhook_secret_encrypted"],
+        array_columns: &[],
+    },
+    EncryptedTable {
+        table: "edi_trading_partners",
+        key: "id",
+        key_type: "uuid",
+        columns: &[
+            "as2_username",
+            "as2_password",
+            "sftp_username",
+            "sftp_password",
+            "sftp_private_key",
+            "as2_inbound_token",
+        ],
+        array_columns: &[],
+    },
+    EncryptedTable {
+        table: "erp_connections",
+        key: "id",
+        key_type: "uuid",
+        columns: &[
+            "netsuite_consumer_key",
+            "netsuite_consumer_secret",
+            "netsuite_token_id",
+            "netsuite_token_secret",
+            "sap_client_id",
+            "sap_client_secret",
+            "sftp_username",
+            "sftp_password",
+            "sftp_private_key",
+            "webhook_secret_encrypted",
+        ],
+        array_columns: &[],
+    },
+    EncryptedTable {
+        table: "webhook_endpoints",
+        key: "id",
+        key_type: "uuid",
+        columns: &["secret_encrypted"],
+        array_columns: &[],
+    },
+    EncryptedTable {
+        table: "sync_source_settings",
+        key: "source",
+        key_type: "text",
+        columns: &["api_key_encrypted"],
+        array_columns: &[],
+    },
+];
+
+impl EncryptedTable {
+    /// Rows with a non-empty ciphertext in any column
+    fn has_ciphertext(&self) -> String {
+        self.columns
+            .iter()
+            .map(|column| format!("COALESCE({}, '') <> ''", column))
+            .chain(self.array_columns.iter().map(|column| format!("COALESCE(cardinality({}), 0) > 0", column)))
+            .collect::<Vec<_>>()
+            .join(" OR ")
+    }
+
+    /// Rows with a ciphertext not matching the pattern bound as `$param`
+    fn has_stale(&self, param: usize) -> String {
+        self.columns
+            .iter()
+            .map(|column| format!("(COALESCE({c}, '') <> '' AND {c} !~ ${p})", c = column, p = param))
+            .chain(self.array_columns.iter().map(|column| {
+                format!(
+                    "EXISTS (SELECT 1 FROM unnest({}) AS e(value) WHERE e.value <> '' AND e.value !~ ${})",
+                    column, param
+                )
+            }))
+            .collect::<Vec<_>>()
+            .join(" OR ")
+    }
+}
+
+/// Regex matching ciphertext of the key `version`
+fn current_pattern(version: i32) -> String {
+    if version == 0 {
+        // Untagged
+        "^[^:]*$".to_string()
+    } else {
+        format!("^v{}:", version)
+    }
+}
+
+/// Percentage of `encrypted_rows` on the active key, rounded down to one decimal so
+/// it only reads 100 when nothing is left
+pub fn percent_migrated(encrypted_rows: i64, current_rows: i64) -> f64 {
+    if encrypted_rows == 0 {
+        100.0
+    } else {
+        (current_rows as f64 * 1000.0 / encrypted_rows as f64).floor() / 10.0
+    }
+}
+
+/// Share of one table's encrypted rows already on the active key
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct TableReencryptionProgress {
+    pub table: String,
+    pub encrypted_rows: i64,
+    pub current_rows: i64,
+    pub percent_migrated: f64,
+}
+
+impl TableReencryptionProgress {
+    fn new(table: &str, encrypted_rows: i64, current_rows: i64) -> Self {
+        Self {
+            table: table.to_string(),
+            encrypted_rows,
+            current_rows,
+            percent_migrated: percent_migrated(encrypted_rows, current_rows),
+        }
+    }
+
+    pub fn pending_rows(&self) -> i64 {
+        self.encrypted_rows - self.current_rows
+    }
+}
+
+/// A value to write back, and the value it replaces
+enum Rewrite {
+    Text { column: &'static str, old: String, new: String },
+    Array { column: &'static str, old: Vec<String>, new: Vec<String> },
+}
+
+pub struct EncryptionReencryptionService {
+    db_pool: PgPool,
+    master_key: String,
+    batch_size: i64,
+    pause: Duration,
+}
+
+impl EncryptionReencryptionService {
+    pub fn new(db_pool: PgPool, master_key: &str) -> Self {
+        let batch_size = std::env::var("ENCRYPTION_REENCRYPT_BATCH_SIZE")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|size| *size > 0)
+            .unwrap_or(DEFAULT_BATCH_SIZE);
+        let pause_ms = std::env::var("ENCRYPTION_REENCRYPT_PAUSE_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_PAUSE_MS);
+
+        Self { db_pool, master_key: master_key.to_string(), batch_size, pause: Duration::from_millis(pause_ms) }
+    }
+
+    fn rotation(&self) -> EncryptionKeyRotationService {
+        EncryptionKeyRotationService::new(self.db_pool.clone(), self.master_key.clone())
+    }
+
+    /// Rows per table already on the active key `key_version`
+    pub async fn progress(&self, key_version: i32) -> Result<Vec<TableReencryptionProgress>> {
+        let pattern = current_pattern(key_version);
+        let mut progress = Vec::with_capacity(ENCRYPTED_TABLES.len());
+
+        for table in ENCRYPTED_TABLES {
+            let (encrypted_rows, current_rows): (i64, i64) = sqlx::query_as(&format!(
+                r#"
+                SELECT COUNT(*) FILTER (WHERE {has}),
+                       COUNT(*) FILTER (WHERE ({has}) AND NOT ({stale}))
+                FROM {table}
+                "#,
+                has = table.has_ciphertext(),
+                stale = table.has_stale(1),
+                table = table.table,
+            ))
+            .bind(&pattern)
+            .fetch_one(&self.db_pool)
+            .await?;
+
+            progress.push(TableReencryptionProgress::new(table.table, encrypted_rows, current_rows));
+        }
+        Ok(progress)
+    }
+
+    /// Re-encrypt every table with the active key; returns the job result
+    pub async fn run(&self) -> Result<serde_json::Value> {
+        let rotation = self.rotation();
+        // Up to date even if this instance's refresh hasn't seen the latest rotation yet
+        let key_version = rotation.load_keyring().await?;
+        let newest = rotation.list_keys().await?.first().map(|key| key.key_version).unwrap_or(0);
+        if newest > key_version {
+            return Err(AppError::Internal(anyhow::anyhow!(
+                "Encryption key version {} isn't active yet; re-encryption will retry",
+                newest
+            )));
+        }
+
+        let encryption = EncryptionService::new(&self.master_key)?;
+        let mut rows_reencrypted = BTreeMap::new();
+        let mut unreadable = 0;
+        for table in ENCRYPTED_TABLES {
+            let (rows, failed) = self.reencrypt_table(&encryption, table, key_version).await?;
+            tracing::info!("Re-encrypted {} {} rows with key version {}", rows, table.table, key_version);
+            rows_reencrypted.insert(table.table, rows);
+            unreadable += failed;
+        }
+
+        // Older keys are done with once no row uses them
+        let progress = self.progress(key_version).await?;
+        let mut retired = Vec::new();
+        if progress.iter().all(|table| table.pending_rows() == 0) {
+            for key in rotation.list_keys().await? {
+                if matches!(key.status, KeyStatus::Deprecated) && key.key_version < key_version {
+                    rotation.mark_key_rotated(key.id).await?;
+                    retired.push(key.key_version);
+                }
+            }
+        }
+
+        Ok(serde_json::json!({
+            "key_version": key_version,
+            "rows_reencrypted": rows_reencrypted,
+            "unreadable_values": unreadable,
+            "keys_rotated": retired,
+        }))
+    }
+
+    /// Returns the rows rewritten and the values that failed to decrypt
+    async fn reencrypt_table(
+        &self,
+        encryption: &EncryptionService,
+        table: &EncryptedTable,
+        key_version: i32,
+    ) -> Result<(i64, i64)> {
+        let columns: Vec<&str> = table.columns.iter().chain(table.array_columns).copied().collect();
+        let select = format!(
+            r#"
+            SELECT {key}::text AS row_key, {columns}
+            FROM {table}
+            WHERE ($1::text IS NULL OR {key} > CAST($1 AS {key_type})) AND ({stale})
+            ORDER BY {key}
+            LIMIT $3
+            "#,
+            key = table.key,
+            columns = columns.join(", "),
+            table = table.table,
+            key_type = table.key_type,
+            stale = table.has_stale(2),
+        );
+        let pattern = current_pattern(key_version);
+
+        let (mut rewritten, mut unreadable) = (0, 0);
+        let mut after: Option<String> = None;
+        loop {
+            // Stop between batches; the retry picks up the rows left
+            if Shutdown::requested() {
+                return Err(Shutdown::interrupted());
+            }
+
+            let rows: Vec<PgRow> = sqlx::query(&select)
+                .bind(&after)
+                .bind(&pattern)
+                .bind(self.batch_size)
+                .fetch_all(&self.db_pool)
+                .await?;
+            let Some(last) = rows.last() else {
+                break;
+            };
+            after = Some(last.try_get("row_key")?);
+
+            for row in &rows {
+                let row_key: String = row.try_get("row_key")?;
+                let (rewrites, failed) = Self::rewrites(encryption, table, row)?;
+                if failed > 0 {
+                    tracing::warn!(
+                        "{} of {} {} couldn't be decrypted for re-encryption",
+                        failed,
+                        table.table,
+                        row_key
+                    );
+                    unreadable += failed;
+                }
+                if !rewrites.is_empty() && self.write(table, &row_key, rewrites).await? {
+                    rewritten += 1;
+                }
+            }
+
+            if (rows.len() as i64) < self.batch_size {
+                break;
+            }
+            tokio::time::sleep(self.pause).await;
+        }
+        Ok((rewritten, unreadable))
+    }
+
+    /// The values of a row to re-encrypt, and how many couldn't be decrypted
+    fn rewrites(encryption: &EncryptionService, table: &EncryptedTable, row: &PgRow) -> Result<(Vec<Rewrite>, i64)> {
+        let mut rewrites = Vec::new();
+        let mut failed = 0;
+
+        for &column in table.columns {
+            let Some(old) = row.try_get::<Option<String>, _>(column)? else {
+                continue;
+            };
+            match encryption.reencrypt(&old) {
+                Ok(Some(new)) => rewrites.push(Rewrite::Text { column, old, new }),
+                Ok(None) => {}
+                Err(_) => failed += 1,
+            }
+        }
+
+        for &column in table.array_columns {
+            let Some(old) = row.try_get::<Option<Vec<String>>, _>(column)? else {
+                continue;
+            };
+            let mut new = Vec::with_capacity(old.len());
+            let mut changed = false;
+            for value in &old {
+                match encryption.reencrypt(value) {
+                    Ok(Some(reencrypted)) => {
+                        new.push(reencrypted);
+                        changed = true;
+                    }
+                    Ok(None) => new.push(value.clone()),
+                    Err(_) => {
+                        new.push(value.clone());
+                        failed += 1;
+                    }
+                }
+            }
+            if changed {
+                rewrites.push(Rewrite::Array { column, old, new });
+            }
+        }
+
+        Ok((rewrites, failed))
+    }
+
+    /// Write the re-encrypted values unless the row changed since it was read
+    async fn write(&self, table: &EncryptedTable, row_key: &str, rewrites: Vec<Rewrite>) -> Result<bool> {
+        let mut assignments = Vec::new();
+        let mut conditions = vec![format!("{} = CAST($1 AS {})", table.key, table.key_type)];
+        for (i, rewrite) in rewrites.iter().enumerate() {
+            let column = match rewrite {
+                Rewrite::Text { column, .. } | Rewrite::Array { column, .. } => column,
+            };
+            assignments.push(format!("{} = ${}", column, 2 * i + 2));
+            conditions.push(format!("{} = ${}", column, 2 * i + 3));
+        }
+
+        let sql = format!(
+            "UPDATE {} SET {} WHERE {}",
+            table.table,
+            assignments.join(", "),
+            conditions.join(" AND ")
+        );
+        let mut query = sqlx::query(&sql).bind(row_key);
+        for rewrite in rewrites {
+            query = match rewrite {
+                Rewrite::Text { old, new, .. } => query.bind(new).bind(old),
+                Rewrite::Array { old, new, .. } => query.bind(new).bind(old),
+            };
+        }
+
+        Ok(query.execute(&self.db_pool).await?.rows_affected() > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_percent() {
+        assert_eq!(TableReencryptionProgress::new("users", 0, 0).percent_migrated, 100.0);
+        assert_eq!(TableReencryptionProgress::new("users", 3, 2).percent_migrated, 66.6);
+        assert_eq!(TableReencryptionProgress::new("users", 200, 199).pending_rows(), 1);
+        assert_eq!(percent_migrated(10_000, 9_999), 99.9);
+        assert_eq!(current_pattern(3), "^v3:");
+    }
+}
@@ -9,6 +9,14 @@
 ///! - GCM: Authenticated encryption (detects tampering)
 ///! - Unique nonce per encryption (prevents replay attacks)
 ///! - Constant-time operations (prevents timing attacks)
+///!
+///! Key versions:
+///! Text is encrypted with the active data encryption key (DEK) of the keyring loaded
+///! by `EncryptionKeyRotationService::load_keyring` and tagged with its version:
+///! "v{version}:base64(...)". Untagged ciphertext is version 0, encrypted with the
+///! ENCRYPTION_KEY itself (everything written before DEKs were used, and every file).
+///! Decryption picks the key by the tag, so old ciphertext stays readable until the
+///! re-encryption job has moved it to the active key.
 
 use aes_gcm::{
     aead::{Aead, KeyInit, OsRng},
@@ -18,7 +26,11 @@ use argon2::{Argon2, PasswordHasher};
 use argon2::password_hash::{rand_core::RngCore, SaltString};
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
 use rand::RngCore as _;
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
 use sha2::{Sha256, Digest};
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -37,54 +49,153 @@ pub enum EncryptionError {
 
     #[error("Key derivation failed: {0}")]
     KeyDerivationFailed(String),
+
+    #[error("Unknown encryption key version {0}")]
+    UnknownKeyVersion(i32),
 }
 
 pub type Result<T> = std::result::Result<T, EncryptionError>;
 
+/// DEKs of the instance, with the fingerprint of the master key they are wrapped with
+static KEYRING: Lazy<RwLock<Option<Arc<Keyring>>>> = Lazy::new(|| RwLock::new(None));
+
+struct Keyring {
+    master_fingerprint: [u8; 32],
+    /// Version -> (cipher, when it becomes the key new ciphertext is written with)
+    keys: BTreeMap<i32, (Aes256Gcm, DateTime<Utc>)>,
+}
+
+impl Keyring {
+    /// The newest key whose activation time has passed
+    fn active(&self) -> Option<(i32, &Aes256Gcm)> {
+        let now = Utc::now();
+        self.keys
+            .iter()
+            .rev()
+            .find(|(_, (_, activates_at))| *activates_at <= now)
+            .map(|(version, (cipher, _))| (*version, cipher))
+    }
+}
+
+/// A decrypted DEK for `EncryptionService::install_keyring`
+#[derive(Clone)]
+pub struct DataKey {
+    pub version: i32,
+    /// Base64-encoded 256-bit key
+    pub key: String,
+    pub activates_at: DateTime<Utc>,
+}
+
 /// Encryption service for sensitive data
 ///
 /// Thread-safe, can be cloned and shared across threads
 #[derive(Clone)]
 pub struct EncryptionService {
     cipher: Aes256Gcm,
+    /// SHA-256 of the key, matching it to the keyring wrapped with it
+    fingerprint: [u8; 32],
+    /// Whether text goes through the keyring; off for the master key wrapping the DEKs
+    versioned: bool,
 }
 
 impl EncryptionService {
     /// Create new encryption service from base64-encoded key
     ///
-    /// Key must be exactly 32 bytes (256 bits) when decoded
+    /// Key must be exactly 32 bytes (256 bits) when decoded. Once the keyring of this
+    /// key is installed, text is encrypted with its active DEK.
     pub fn new(base64_key: &str) -> Result<Self> {
+        let (cipher, fingerprint) = Self::key_cipher(base64_key)?;
+        Ok(Self { cipher, fingerprint, versioned: true })
+    }
+
+    /// Encryption service that always uses the key itself, never the keyring
+    ///
+    /// For wrapping the DEKs with the master key.
+    pub fn master(base64_key: &str) -> Result<Self> {
+        let (cipher, fingerprint) = Self::key_cipher(base64_key)?;
+        Ok(Self { cipher, fingerprint, versioned: false })
+    }
+
+    fn key_cipher(base64_key: &str) -> Result<(Aes256Gcm, [u8; 32])> {
         let key_bytes = BASE64
             .decode(base64_key)
-            .map_err(|e| EncryptionError::InvalidKey)?;
+            .map_err(|_| EncryptionError::InvalidKey)?;
 
         if key_bytes.len() != 32 {
             return Err(EncryptionError::InvalidKey);
         }
 
         let cipher = Aes256Gcm::new_from_slice(&key_bytes)
-            .map_err(|e| EncryptionError::InvalidKey)?;
+            .map_err(|_| EncryptionError::InvalidKey)?;
+
+        Ok((cipher, Sha256::digest(&key_bytes).into()))
+    }
+
+    /// Replace the keyring of `master_key` with `keys`
+    ///
+    /// Every service created from `master_key` picks it up on its next operation.
+    pub fn install_keyring(master_key: &str, keys: Vec<DataKey>) -> Result<()> {
+        let (_, master_fingerprint) = Self::key_cipher(master_key)?;
+        let keys = keys
+            .into_iter()
+            .map(|key| Ok((key.version, (Self::key_cipher(&key.key)?.0, key.activates_at))))
+            .collect::<Result<BTreeMap<_, _>>>()?;
+
+        *KEYRING.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(Keyring { master_fingerprint, keys }));
+        Ok(())
+    }
+
+    fn keyring(&self) -> Option<Arc<Keyring>> {
+        if !self.versioned {
+            return None;
+        }
+        KEYRING
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .filter(|keyring| keyring.master_fingerprint == self.fingerprint)
+            .cloned()
+    }
+
+    /// Version of the key new text is encrypted with (0 for the key itself)
+    pub fn active_key_version(&self) -> i32 {
+        self.keyring().and_then(|keyring| keyring.active().map(|(version, _)| version)).unwrap_or(0)
+    }
+
+    /// Version of the key a ciphertext was encrypted with (0 when untagged)
+    pub fn key_version(ciphertext: &str) -> i32 {
+        Self::split_version(ciphertext).0
+    }
 
-        Ok(Self { cipher })
+    fn split_version(ciphertext: &str) -> (i32, &str) {
+        // Base64 has no ':', so only a version tag can contain one
+        ciphertext
+            .strip_prefix('v')
+            .and_then(|rest| rest.split_once(':'))
+            .and_then(|(version, encoded)| Some((version.parse().ok()?, encoded)))
+            .unwrap_or((0, ciphertext))
     }
 
     /// Encrypt plaintext data
     ///
     /// Returns base64-encoded string: nonce(12 bytes) || ciphertext || tag(16 bytes)
-    /// Format: "base64(nonce + encrypted_data + auth_tag)"
+    /// Format: "v{key version}:base64(nonce + encrypted_data + auth_tag)", without the
+    /// tag while no DEK is active
     pub fn encrypt(&self, plaintext: &str) -> Result<String> {
         if plaintext.is_empty() {
             return Ok(String::new());
         }
 
+        let keyring = self.keyring();
+        let (version, cipher) = keyring.as_deref().and_then(Keyring::active).unwrap_or((0, &self.cipher));
+
         // Generate unique 96-bit nonce (12 bytes) - MUST be unique per encryption
         let mut nonce_bytes = [0u8; 12];
         rand::thread_rng().fill_bytes(&mut nonce_bytes);
         let nonce = Nonce::from_slice(&nonce_bytes);
 
         // Encrypt plaintext with authentication
-        let ciphertext = self
-            .cipher
+        let ciphertext = cipher
             .encrypt(nonce, plaintext.as_bytes())
             .map_err(|e| EncryptionError::EncryptionFailed(e.to_string()))?;
 
@@ -94,21 +205,34 @@ impl EncryptionService {
         combined.extend_from_slice(&ciphertext);
 
         // Encode as base64 for database storage
-        Ok(BASE64.encode(&combined))
+        let encoded = BASE64.encode(&combined);
+        Ok(if version == 0 { encoded } else { format!("v{}:{}", version, encoded) })
     }
 
     /// Decrypt ciphertext data
     ///
-    /// Expects base64-encoded string containing nonce + encrypted_data + auth_tag
+    /// Expects base64-encoded string containing nonce + encrypted_data + auth_tag, with
+    /// the version tag of its key if any
     pub fn decrypt(&self, ciphertext: &str) -> Result<String> {
         if ciphertext.is_empty() {
             return Ok(String::new());
         }
 
+        let (version, encoded) = Self::split_version(ciphertext);
+        let keyring = self.keyring();
+        let cipher = match version {
+            0 => &self.cipher,
+            version => keyring
+                .as_deref()
+                .and_then(|keyring| keyring.keys.get(&version))
+                .map(|(cipher, _)| cipher)
+                .ok_or(EncryptionError::UnknownKeyVersion(version))?,
+        };
+
         // Decode from base64
         let combined = BASE64
-            .decode(ciphertext)
-            .map_err(|e| EncryptionError::InvalidFormat)?;
+            .decode(encoded)
+            .map_err(|_| EncryptionError::InvalidFormat)?;
 
         // Must have at least nonce (12) + tag (16) = 28 bytes
         if combined.len() < 28 {
@@ -120,8 +244,7 @@ impl EncryptionService {
         let nonce = Nonce::from_slice(nonce_bytes);
 
         // Decrypt and verify authentication tag
-        let plaintext_bytes = self
-            .cipher
+        let plaintext_bytes = cipher
             .decrypt(nonce, encrypted_data)
             .map_err(|e| EncryptionError::DecryptionFailed(e.to_string()))?;
 
@@ -130,6 +253,16 @@ impl EncryptionService {
             .map_err(|e| EncryptionError::DecryptionFailed("Invalid UTF-8".to_string()))
     }
 
+    /// Re-encrypt ciphertext with the active key
+    ///
+    /// Returns None when it already uses the active key.
+    pub fn reencrypt(&self, ciphertext: &str) -> Result<Option<String>> {
+        if ciphertext.is_empty() || Self::key_version(ciphertext) == self.active_key_version() {
+            return Ok(None);
+        }
+        Ok(Some(self.encrypt(&self.decrypt(ciphertext)?)?))
+    }
+
     /// Encrypt optional string (for Option<String> fields)
     pub fn encrypt_optional(&self, plaintext: Option<&String>) -> Result<Option<String>> {
         match plaintext {
@@ -175,6 +308,7 @@ impl EncryptionService {
     /// Encrypt binary data (for files like Excel, images, etc.)
     ///
     /// Returns base64-encoded string: nonce(12 bytes) || ciphertext || tag(16 bytes)
+    /// Files are always encrypted with the key itself, not a DEK.
     pub fn encrypt_bytes(&self, plaintext: &[u8]) -> Result<String> {
         if plaintext.is_empty() {
             return Ok(String::new());
@@ -310,4 +444,44 @@ mod tests {
         let encrypted = service.encrypt_optional(none_data.as_ref()).unwrap();
         assert!(encrypted.is_none());
     }
+
+    #[test]
+    fn test_versioned_keyring() {
+        let master_key = EncryptionService::generate_key();
+        let service = EncryptionService::new(&master_key).unwrap();
+        let legacy = service.encrypt("legacy data").unwrap();
+        assert_eq!(EncryptionService::key_version(&legacy), 0);
+
+        let now = Utc::now();
+        EncryptionService::install_keyring(
+            &master_key,
+            vec![
+                DataKey { version: 1, key: EncryptionService::generate_key(), activates_at: now },
+                DataKey {
+                    version: 2,
+                    key: EncryptionService::generate_key(),
+                    activates_at: now + chrono::Duration::minutes(5),
+                },
+            ],
+        )
+        .unwrap();
+
+        // Version 2 isn't active yet; legacy ciphertext stays readable
+        let tagged = service.encrypt("new data").unwrap();
+        assert!(tagged.starts_with("v1:"));
+        assert_eq!(service.decrypt(&tagged).unwrap(), "new data");
+        assert_eq!(service.decrypt(&legacy).unwrap(), "legacy data");
+
+        let reencrypted = service.reencrypt(&legacy).unwrap().unwrap();
+        assert_eq!(EncryptionService::key_version(&reencrypted), 1);
+        assert_eq!(service.decrypt(&reencrypted).unwrap(), "legacy data");
+        assert!(service.reencrypt(&reencrypted).unwrap().is_none());
+
+        // The master key service and services of other keys ignore the keyring
+        let master = EncryptionService::master(&master_key).unwrap();
+        assert_eq!(EncryptionService::key_version(&master.encrypt("dek").unwrap()), 0);
+        assert!(matches!(master.decrypt(&tagged), Err(EncryptionError::UnknownKeyVersion(1))));
+        let other = EncryptionService::new(&EncryptionService::generate_key()).unwrap();
+        assert_eq!(other.active_key_version(), 0);
+    }
 }
@@ -0,0 +1,319 @@
+// ERP API Guard
+// Per-connection rate budget (token bucket) and circuit breaker shared by every NetSuite
+// and SAP client of a connection in this process. Consecutive 5xx/429 responses open the
+// circuit; after a cool-down a single half-open probe decides whether it closes again.
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use utoipa::ToSchema;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// Guards by connection ID
+static GUARDS: Lazy<DashMap<Uuid, Arc<ErpApiGuard>>> = Lazy::new(DashMap::new);
+
+/// The guard of a connection, created on first use
+pub fn guard_for(connection_id: Uuid) -> Arc<ErpApiGuard> {
+    GUARDS
+        .entry(connection_id)
+        .or_insert_with(|| Arc::new(ErpApiGuard::new(ApiGuardConfig::default())))
+        .clone()
+}
+
+/// Circuit status of a connection; closed when no ERP call has been made yet
+pub fn circuit_status(connection_id: Uuid) -> CircuitStatus {
+    GUARDS
+        .get(&connection_id)
+        .map(|guard| guard.status())
+        .unwrap_or_default()
+}
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+#[derive(Debug, Clone)]
+pub struct ApiGuardConfig {
+    /// Burst size of the token bucket
+    pub capacity: f64,
+    /// Sustained requests per second
+    pub refill_per_second: f64,
+    /// Consecutive 5xx/429 responses that open the circuit
+    pub failure_threshold: u32,
+    /// How long the circuit stays open before a half-open probe
+    pub cool_down: Duration,
+}
+
+impl Default for ApiGuardConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 20.0,
+            refill_per_second: 4.0,
+            failure_threshold: 5,
+            cool_down: Duration::from_secs(60),
+        }
+    }
+}
+
+// ============================================================================
+// Circuit State
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    Closed,
+    /// ERP calls fail fast until the cool-down elapses
+    Open,
+    /// One probe request is let through; its outcome closes or re-opens the circuit
+    HalfOpen,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CircuitStatus {
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    pub opened_at: Option<DateTime<Utc>>,
+    /// Seconds until the next probe while the circuit is open
+    pub retry_after_seconds: Option<u64>,
+}
+
+impl Default for CircuitStatus {
+    fn default() -> Self {
+        Self {
+            state: CircuitState::Closed,
+            consecutive_failures: 0,
+            opened_at: None,
+            retry_after_seconds: None,
+        }
+    }
+}
+
+/// Why a request was not let through
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Denied {
+    /// Circuit open (or probe in flight): fail fast, retry after this long
+    CircuitOpen(Duration),
+    /// Bucket empty: wait this long for a token
+    RateLimited(Duration),
+}
+
+struct GuardState {
+    tokens: f64,
+    last_refill: Instant,
+    circuit: CircuitState,
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    opened_at_utc: Option<DateTime<Utc>>,
+    probe_in_flight: bool,
+}
+
+// ============================================================================
+// Guard
+// ============================================================================
+
+pub struct ErpApiGuard {
+    config: ApiGuardConfig,
+    state: Mutex<GuardState>,
+}
+
+impl ErpApiGuard {
+    pub fn new(config: ApiGuardConfig) -> Self {
+        let state = GuardState {
+            tokens: config.capacity,
+            last_refill: Instant::now(),
+            circuit: CircuitState::Closed,
+            consecutive_failures: 0,
+            opened_at: None,
+            opened_at_utc: None,
+            probe_in_flight: false,
+        };
+
+        Self {
+            config,
+            state: Mutex::new(state),
+        }
+    }
+
+    /// Wait for a token. Returns the remaining cool-down when the circuit is open.
+    pub async fn acquire(&self) -> std::result::Result<(), Duration> {
+        loop {
+            match self.try_acquire(Instant::now()) {
+                Ok(()) => return Ok(()),
+                Err(Denied::CircuitOpen(retry_after)) => return Err(retry_after),
+                Err(Denied::RateLimited(wait)) => tokio::time::sleep(wait).await,
+            }
+        }
+    }
+
+    /// Record the HTTP status of a guarded request
+    pub fn record_status(&self, status: reqwest::StatusCode) {
+        if status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            self.record_failure(Instant::now());
+        } else {
+            self.record_success();
+        }
+    }
+
+    /// Record a request that never got a response (the probe slot is released)
+    pub fn record_no_response(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.probe_in_flight = false;
+    }
+
+    pub fn status(&self) -> CircuitStatus {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let retry_after_seconds = match (state.circuit, state.opened_at) {
+            (CircuitState::Open, Some(opened_at)) => {
+                Some(self.config.cool_down.saturating_sub(opened_at.elapsed()).as_secs())
+            }
+            _ => None,
+        };
+
+        CircuitStatus {
+            state: state.circuit,
+            consecutive_failures: state.consecutive_failures,
+            opened_at: state.opened_at_utc,
+            retry_after_seconds,
+        }
+    }
+
+    fn try_acquire(&self, now: Instant) -> std::result::Result<(), Denied> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        match state.circuit {
+            CircuitState::Open => {
+                let opened_at = state.opened_at.unwrap_or(now);
+                let elapsed = now.saturating_duration_since(opened_at);
+                if elapsed < self.config.cool_down {
+                    return Err(Denied::CircuitOpen(self.config.cool_down - elapsed));
+                }
+                state.circuit = CircuitState::HalfOpen;
+                state.probe_in_flight = false;
+            }
+            CircuitState::HalfOpen if state.probe_in_flight => {
+                return Err(Denied::CircuitOpen(Duration::from_secs(1)));
+            }
+            _ => {}
+        }
+
+        // Refill the bucket
+        let elapsed = now.saturating_duration_since(state.last_refill).as_secs_f64();
+        state.tokens = (state.tokens + elapsed * self.config.refill_per_second).min(self.config.capacity);
+        state.last_refill = now;
+
+        if state.tokens < 1.0 {
+            let wait = (1.0 - state.tokens) / self.config.refill_per_second;
+            return Err(Denied::RateLimited(Duration::from_secs_f64(wait)));
+        }
+
+        state.tokens -= 1.0;
+        if state.circuit == CircuitState::HalfOpen {
+            state.probe_in_flight = true;
+        }
+
+        Ok(())
+    }
+
+    fn record_success(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.circuit != CircuitState::Closed {
+            tracing::info!("ERP circuit closed after successful probe");
+        }
+        state.circuit = CircuitState::Closed;
+        state.consecutive_failures = 0;
+        state.opened_at = None;
+        state.opened_at_utc = None;
+        state.probe_in_flight = false;
+    }
+
+    fn record_failure(&self, now: Instant) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.consecutive_failures += 1;
+        state.probe_in_flight = false;
+
+        let reopen = state.circuit == CircuitState::HalfOpen;
+        if reopen || (state.circuit == CircuitState::Closed && state.consecutive_failures >= self.config.failure_threshold) {
+            tracing::warn!(
+                "ERP circuit opened after {} consecutive failures",
+                state.consecutive_failures
+            );
+            state.circuit = CircuitState::Open;
+            state.opened_at = Some(now);
+            state.opened_at_utc = Some(Utc::now());
+        }
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn guard(capacity: f64, threshold: u32) -> ErpApiGuard {
+        ErpApiGuard::new(ApiGuardConfig {
+            capacity,
+            refill_per_second: 1.0,
+            failure_threshold: threshold,
+            cool_down: Duration::from_secs(30),
+        })
+    }
+
+    #[test]
+    fn test_bucket_limits_burst_and_refills() {
+        let guard = guard(2.0, 5);
+        let start = Instant::now();
+
+        assert!(guard.try_acquire(start).is_ok());
+        assert!(guard.try_acquire(start).is_ok());
+        assert!(matches!(guard.try_acquire(start), Err(Denied::RateLimited(_))));
+        assert!(guard.try_acquire(start + Duration::from_secs(1)).is_ok());
+    }
+
+    #[test]
+    fn test_circuit_opens_after_consecutive_failures() {
+        let guard = guard(10.0, 3);
+        let start = Instant::now();
+
+        guard.record_failure(start);
+        guard.record_failure(start);
+        guard.record_success();
+        guard.record_failure(start);
+        guard.record_failure(start);
+        assert_eq!(guard.status().state, CircuitState::Closed);
+
+        guard.record_failure(start);
+        assert_eq!(guard.status().state, CircuitState::Open);
+        assert!(matches!(guard.try_acquire(start), Err(Denied::CircuitOpen(_))));
+    }
+
+    #[test]
+    fn test_half_open_probe_closes_or_reopens() {
+        let guard = guard(10.0, 1);
+        let start = Instant::now();
+        guard.record_failure(start);
+
+        // After the cool-down one probe is let through, others fail fast
+        let later = start + Duration::from_secs(31);
+        assert!(guard.try_acquire(later).is_ok());
+        assert_eq!(guard.status().state, CircuitState::HalfOpen);
+        assert!(matches!(guard.try_acquire(later), Err(Denied::CircuitOpen(_))));
+
+        // A failed probe re-opens
+        guard.record_failure(later);
+        assert_eq!(guard.status().state, CircuitState::Open);
+
+        // A successful probe closes
+        let much_later = later + Duration::from_secs(31);
+        assert!(guard.try_acquire(much_later).is_ok());
+        guard.record_success();
+        assert_eq!(guard.status().state, CircuitState::Closed);
+        assert_eq!(guard.status().consecutive_failures, 0);
+    }
+}
@@ -0,0 +1,159 @@
+// ERP Bulk Sync
+// Batch planning for ERP -> Atlas quantity syncs. A page of ERP quantities is resolved
+// against the current Atlas quantities in memory, so the sync service reads and writes
+// each chunk with one statement instead of a round trip per item.
+
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::services::erp::ConflictResolution;
+
+/// Inventory rows read and written per statement
+pub const UPSERT_CHUNK_SIZE: usize = 1000;
+
+/// Materials per SAP `$filter`; keeps the request URL well below gateway limits
+pub const SAP_MATERIAL_BATCH_SIZE: usize = 40;
+
+/// Item IDs per NetSuite `ANY_OF` query
+pub const NETSUITE_ITEM_BATCH_SIZE: usize = 100;
+
+/// ERP quantity reported for a mapped Atlas inventory item
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuantityChange {
+    pub mapping_id: Uuid,
+    pub inventory_id: Uuid,
+    pub erp_quantity: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlannedOutcome {
+    /// The ERP quantity is written to Atlas
+    Write,
+    /// Atlas already holds the ERP quantity
+    Unchanged,
+    /// Atlas wins: the ERP value is ignored
+    Kept,
+    /// Manual resolution: the difference is queued for review
+    Conflict { atlas_quantity: i32 },
+    /// The mapped inventory no longer exists
+    Missing,
+}
+
+impl PlannedOutcome {
+    /// Whether the mapping's last sync time advances
+    pub fn is_synced(&self) -> bool {
+        matches!(self, PlannedOutcome::Write | PlannedOutcome::Unchanged)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct QuantityPlan {
+    /// One outcome per change, in input order
+    pub outcomes: Vec<PlannedOutcome>,
+    /// Inventory quantities to write, one per inventory item
+    pub writes: Vec<(Uuid, i32)>,
+}
+
+impl QuantityPlan {
+    /// Mappings whose last sync time advances
+    pub fn synced_mappings(&self, changes: &[QuantityChange]) -> Vec<Uuid> {
+        changes
+            .iter()
+            .zip(&self.outcomes)
+            .filter(|(_, outcome)| outcome.is_synced())
+            .map(|(change, _)| change.mapping_id)
+            .collect()
+    }
+}
+
+/// Resolve ERP quantities against the current Atlas quantities following the
+/// connection's conflict resolution. When several changes reach the same inventory item
+/// the last one is written.
+pub fn plan_quantity_changes(
+    changes: &[QuantityChange],
+    atlas_quantities: &HashMap<Uuid, i32>,
+    resolution: &ConflictResolution,
+) -> QuantityPlan {
+    let mut outcomes = Vec::with_capacity(changes.len());
+    let mut writes: Vec<(Uuid, i32)> = Vec::new();
+    let mut write_index: HashMap<Uuid, usize> = HashMap::new();
+
+    for change in changes {
+        let outcome = match atlas_quantities.get(&change.inventory_id) {
+            None => PlannedOutcome::Missing,
+            Some(&atlas_quantity) if atlas_quantity == change.erp_quantity => PlannedOutcome::Unchanged,
+            Some(&atlas_quantity) => match resolution {
+                ConflictResolution::ErpWins | ConflictResolution::LatestTimestamp => PlannedOutcome::Write,
+                ConflictResolution::AtlasWins => PlannedOutcome::Kept,
+                ConflictResolution::Manual => PlannedOutcome::Conflict { atlas_quantity },
+            },
+        };
+
+        if outcome == PlannedOutcome::Write {
+            match write_index.get(&change.inventory_id) {
+                Some(&index) => writes[index].1 = change.erp_quantity,
+                None => {
+                    write_index.insert(change.inventory_id, writes.len());
+                    writes.push((change.inventory_id, change.erp_quantity));
+                }
+            }
+        }
+
+        outcomes.push(outcome);
+    }
+
+    QuantityPlan { outcomes, writes }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(inventory_id: Uuid, erp_quantity: i32) -> QuantityChange {
+        QuantityChange { mapping_id: Uuid::new_v4(), inventory_id, erp_quantity }
+    }
+
+    #[test]
+    fn test_plan_erp_wins() {
+        let (a, b, missing) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let atlas = HashMap::from([(a, 10), (b, 5)]);
+        let changes = [change(a, 12), change(b, 5), change(missing, 1)];
+
+        let plan = plan_quantity_changes(&changes, &atlas, &ConflictResolution::ErpWins);
+
+        assert_eq!(
+            plan.outcomes,
+            vec![PlannedOutcome::Write, PlannedOutcome::Unchanged, PlannedOutcome::Missing]
+        );
+        assert_eq!(plan.writes, vec![(a, 12)]);
+        assert_eq!(plan.synced_mappings(&changes), vec![changes[0].mapping_id, changes[1].mapping_id]);
+    }
+
+    #[test]
+    fn test_plan_atlas_wins_and_manual() {
+        let a = Uuid::new_v4();
+        let atlas = HashMap::from([(a, 10)]);
+        let changes = [change(a, 7)];
+
+        let kept = plan_quantity_changes(&changes, &atlas, &ConflictResolution::AtlasWins);
+        assert_eq!(kept.outcomes, vec![PlannedOutcome::Kept]);
+        assert!(kept.writes.is_empty());
+        assert!(kept.synced_mappings(&changes).is_empty());
+
+        let manual = plan_quantity_changes(&changes, &atlas, &ConflictResolution::Manual);
+        assert_eq!(manual.outcomes, vec![PlannedOutcome::Conflict { atlas_quantity: 10 }]);
+        assert!(manual.writes.is_empty());
+    }
+
+    #[test]
+    fn test_plan_last_change_wins_per_inventory() {
+        let a = Uuid::new_v4();
+        let atlas = HashMap::from([(a, 1)]);
+        let changes = [change(a, 3), change(a, 4)];
+
+        let plan = plan_quantity_changes(&changes, &atlas, &ConflictResolution::LatestTimestamp);
+
+        assert_eq!(plan.writes, vec![(a, 4)]);
+        assert_eq!(plan.outcomes, vec![PlannedOutcome::Write, PlannedOutcome::Write]);
+    }
+}
@@ -5,12 +5,13 @@
 use uuid::Uuid;
 use sqlx::PgPool;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use crate::middleware::error_handling::{Result, AppError};
 use crate::services::claude_ai_service::{ClaudeAIService, ClaudeRequestConfig, user_message};
-use crate::services::erp::{ErpConnection, ErpType, ConnectionStatus, ConflictResolution};
+use crate::services::ai_response_cache_service::AiFeature;
+use crate::services::erp::{ErpConnection, ConnectionStatus, ConflictResolution};
 use crate::services::erp::erp_connection_service::{SyncDirection, ErpConnectionService};
-use crate::services::erp::netsuite_client::{NetSuiteClient, NetSuiteSearchParams, NetSuiteError};
-use crate::services::erp::sap_client::{SapClient, SapError};
+use crate::services::erp::ErpItemCatalogService;
 use std::collections::HashMap;
 use rust_decimal::Decimal;
 
@@ -126,7 +127,7 @@ Your response MUST be valid JSON:
 // Request/Response Models
 // ============================================================================
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct MappingSuggestion {
     pub atlas_inventory_id: Uuid,
     pub erp_item_id: String,
@@ -137,7 +138,7 @@ pub struct MappingSuggestion {
     pub reasoning: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct MappingDiscoveryResponse {
     pub mappings: Vec<MappingSuggestion>,
     pub unmapped_atlas_items: Vec<Uuid>,
@@ -145,7 +146,7 @@ pub struct MappingDiscoveryResponse {
     pub warnings: Vec<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct SyncInsight {
     pub insight_type: String,
     pub severity: String,
@@ -155,14 +156,14 @@ pub struct SyncInsight {
     pub actionable: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct Recommendation {
     pub action: String,
     pub priority: String,
     pub description: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct ConflictResolutionSuggestion {
     pub conflict_type: String,
     pub suggested_resolution: String,
@@ -172,7 +173,7 @@ pub struct ConflictResolutionSuggestion {
     pub evidence: serde_json::Value,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct ConflictResolutionResponse {
     pub resolutions: Vec<ConflictResolutionSuggestion>,
 }
@@ -304,16 +305,15 @@ Provide mapping suggestions with confidence scores. Focus on NDC code matches fi
             system_prompt: Some(MAPPING_DISCOVERY_SYSTEM_PROMPT.to_string()),
         };
 
-        let ai_response = self.claude_service.send_message(
+        let validated = self.claude_service.send_message_validated::<MappingDiscoveryResponse>(
             vec![user_message(&prompt)],
             config,
             user_id,
             None,
+            AiFeature::ErpMappingDiscovery,
         ).await?;
-
-        // Parse AI response
-        let discovery_response: MappingDiscoveryResponse = serde_json::from_str(&ai_response.content)
-            .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to parse AI mapping response: {}", e)))?;
+        let (ai_response, discovery_response) = (validated.response, validated.value);
+        self.claude_service.cache_response(&ai_response).await;
 
         // Save suggestions to database
         for suggestion in &discovery_response.mappings {
@@ -381,16 +381,15 @@ Provide clear explanation of what happened, why it happened, and what to do next
             system_prompt: Some(SYNC_ANALYSIS_SYSTEM_PROMPT.to_string()),
         };
 
-        let ai_response = self.claude_service.send_message(
+        let validated = self.claude_service.send_message_validated::<SyncInsight>(
             vec![user_message(&prompt)],
             config,
             user_id,
             None,
+            AiFeature::ErpSyncAnalysis,
         ).await?;
-
-        // Parse response
-        let insight: SyncInsight = serde_json::from_str(&ai_response.content)
-            .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to parse AI sync analysis: {}", e)))?;
+        let (ai_response, insight) = (validated.response, validated.value);
+        self.claude_service.cache_response(&ai_response).await;
 
         // Save insight to database
         self.save_sync_insight(sync_log_id, &sync_log, &insight).await?;
@@ -446,16 +445,15 @@ For each conflict, recommend resolution with confidence and risk assessment."#,
             system_prompt: Some(CONFLICT_RESOLUTION_SYSTEM_PROMPT.to_string()),
         };
 
-        let ai_response = self.claude_service.send_message(
+        let validated = self.claude_service.send_message_validated::<ConflictResolutionResponse>(
             vec![user_message(&prompt)],
             config,
             user_id,
             None,
+            AiFeature::ErpConflictResolution,
         ).await?;
-
-        // Parse response
-        let resolution_response: ConflictResolutionResponse = serde_json::from_str(&ai_response.content)
-            .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to parse AI conflict resolution: {}", e)))?;
+        let (ai_response, resolution_response) = (validated.response, validated.value);
+        self.claude_service.cache_response(&ai_response).await;
 
         // Save resolutions to database
         for (conflict, resolution) in conflicts.iter().zip(resolution_response.resolutions.iter()) {
@@ -660,190 +658,19 @@ For each conflict, recommend resolution with confidence and risk assessment."#,
         }).collect())
     }
 
+    /// ERP items from the connection's catalog cache, refreshed from the ERP when stale
     async fn fetch_erp_inventory(&self, connection: &ErpConnection) -> Result<Vec<ErpInventoryItem>> {
-        tracing::info!("Fetching real inventory from {} ERP", connection.erp_type.as_str());
-
-        match connection.erp_type {
-            ErpType::NetSuite => self.fetch_netsuite_inventory(connection).await,
-            ErpType::SapS4Hana => self.fetch_sap_inventory(connection).await,
-        }
-    }
-
-    /// Fetch inventory from NetSuite via SuiteTalk REST API
-    async fn fetch_netsuite_inventory(&self, connection: &ErpConnection) -> Result<Vec<ErpInventoryItem>> {
-        let netsuite_config = connection.netsuite_config.as_ref()
-            .ok_or_else(|| AppError::Internal(anyhow::anyhow!("NetSuite credentials not found")))?;
-
-        // Create NetSuite client
-        let client = NetSuiteClient::new(netsuite_config.clone())
-            .map_err(|e| self.map_netsuite_error(e))?;
-
-        // Search for inventory items (limit to 1000 for performance)
-        let search_params = NetSuiteSearchParams {
-            q: None, // Get all inventory items
-            limit: Some(1000),
-            offset: Some(0),
-            fields: Some(vec![
-                "id".to_string(),
-                "itemId".to_string(),
-                "displayName".to_string(),
-                "quantityOnHand".to_string(),
-                "custitem_ndc_code".to_string(),
-                "custitem_lot_number".to_string(),
-                "custitem_expiry_date".to_string(),
-                "description".to_string(),
-                "manufacturer".to_string(),
-            ]),
-        };
-
-        tracing::info!("Calling NetSuite inventory search API...");
-        let search_result = client.search_inventory(search_params).await
-            .map_err(|e| self.map_netsuite_error(e))?;
-
-        tracing::info!("NetSuite returned {} inventory items", search_result.items.len());
-
-        // Transform NetSuite items to generic ERP inventory items
-        let erp_items = search_result.items.into_iter().map(|ns_item| {
-            let mut custom_fields = HashMap::new();
-
-            // Add NDC code if present
-            if let Some(ndc) = ns_item.ndc_code {
-                custom_fields.insert("ndc_code".to_string(), ndc);
-            }
-
-            // Add lot number if present
-            if let Some(lot) = ns_item.lot_number {
-                custom_fields.insert("lot_number".to_string(), lot);
-            }
-
-            // Add expiry date if present
-            if let Some(expiry) = ns_item.expiry_date {
-                custom_fields.insert("expiry_date".to_string(), expiry);
-            }
-
-            // Add manufacturer if present
-            if let Some(ref mfg) = ns_item.manufacturer {
-                custom_fields.insert("manufacturer".to_string(), mfg.name.clone());
-            }
-
-            ErpInventoryItem {
-                id: ns_item.id,
-                name: ns_item.display_name,
-                description: ns_item.description,
-                quantity: ns_item.quantity_on_hand.unwrap_or(0.0),
-                custom_fields,
-            }
-        }).collect();
-
-        Ok(erp_items)
-    }
-
-    /// Fetch inventory from SAP via OData API
-    async fn fetch_sap_inventory(&self, connection: &ErpConnection) -> Result<Vec<ErpInventoryItem>> {
-        let sap_config = connection.sap_config.as_ref()
-            .ok_or_else(|| AppError::Internal(anyhow::anyhow!("SAP credentials not found")))?;
-
-        // Create SAP client
-        let client = SapClient::new(sap_config.clone())
-            .map_err(|e| self.map_sap_error(e))?;
-
-        tracing::info!("Fetching SAP product master data...");
-
-        // For SAP, we need to search products and then get their stock
-        // This is a simplified approach - in production you might want pagination
-        let products = client.search_products("").await // Empty search gets all products (limited by SAP)
-            .map_err(|e| self.map_sap_error(e))?;
-
-        tracing::info!("SAP returned {} products, fetching stock data...", products.len());
-
-        let mut erp_items = Vec::new();
-
-        // For each product, get stock information
-        for product in products.into_iter().take(1000) { // Limit to 1000 for performance
-            // Get stock for all locations
-            let stock_result = client.get_material_stock_all_locations(&product.product).await;
-
-            match stock_result {
-                Ok(stock_locations) => {
-                    // Calculate total quantity across all locations
-                    let total_quantity: f64 = stock_locations.iter()
-                        .filter_map(|loc| loc.stock_quantity.parse::<f64>().ok())
-                        .sum();
-
-                    let mut custom_fields = HashMap::new();
-
-                    // Add manufacturer if present
-                    if let Some(mfg) = product.manufacturer {
-                        custom_fields.insert("manufacturer".to_string(), mfg);
-                    }
-
-                    // Add product group if present
-                    if let Some(group) = product.product_group {
-                        custom_fields.insert("product_group".to_string(), group);
-                    }
-
-                    // Add base unit
-                    custom_fields.insert("base_unit".to_string(), product.base_unit.clone());
-
-                    erp_items.push(ErpInventoryItem {
-                        id: product.product.clone(),
-                        name: product.product, // SAP uses material number as name
-                        description: product.description,
-                        quantity: total_quantity,
-                        custom_fields,
-                    });
-                }
-                Err(e) => {
-                    // Log error but continue processing other products
-                    tracing::warn!("Failed to get stock for product {}: {:?}", product.product, e);
-                    // Still add the product with zero quantity
-                    erp_items.push(ErpInventoryItem {
-                        id: product.product.clone(),
-                        name: product.product,
-                        description: product.description,
-                        quantity: 0.0,
-                        custom_fields: HashMap::new(),
-                    });
-                }
-            }
-        }
-
-        tracing::info!("SAP inventory fetch complete: {} items with stock data", erp_items.len());
-
-        Ok(erp_items)
-    }
-
-    /// Map NetSuite errors to AppError
-    fn map_netsuite_error(&self, error: NetSuiteError) -> AppError {
-        match error {
-            NetSuiteError::AuthError(msg) => {
-                tracing::error!("NetSuite authentication failed: {}", msg);
-                AppError::Unauthorized
-            },
-            NetSuiteError::RateLimitExceeded => AppError::TooManyRequests("NetSuite API rate limit exceeded. Please try again later.".to_string()),
-            NetSuiteError::NotFound(msg) => AppError::NotFound(format!("NetSuite resource not found: {}", msg)),
-            NetSuiteError::ApiError(status, msg) => AppError::Internal(anyhow::anyhow!("NetSuite API error ({}): {}", status, msg)),
-            NetSuiteError::NetworkError(e) => AppError::Internal(anyhow::anyhow!("NetSuite network error: {}", e)),
-            NetSuiteError::ConfigError(msg) => AppError::BadRequest(format!("NetSuite configuration error: {}", msg)),
-            _ => AppError::Internal(anyhow::anyhow!("NetSuite error: {:?}", error)),
-        }
-    }
-
-    /// Map SAP errors to AppError
-    fn map_sap_error(&self, error: SapError) -> AppError {
-        match error {
-            SapError::AuthError(msg) => {
-                tracing::error!("SAP authentication failed: {}", msg);
-                AppError::Unauthorized
-            },
-            SapError::RateLimitExceeded => AppError::TooManyRequests("SAP API rate limit exceeded. Please try again later.".to_string()),
-            SapError::NotFound(msg) => AppError::NotFound(format!("SAP resource not found: {}", msg)),
-            SapError::ApiError(status, msg) => AppError::Internal(anyhow::anyhow!("SAP API error ({}): {}", status, msg)),
-            SapError::NetworkError(e) => AppError::Internal(anyhow::anyhow!("SAP network error: {}", e)),
-            SapError::ConfigError(msg) => AppError::BadRequest(format!("SAP configuration error: {}", msg)),
-            SapError::ODataError(msg) => AppError::Internal(anyhow::anyhow!("SAP OData error: {}", msg)),
-            _ => AppError::Internal(anyhow::anyhow!("SAP error: {:?}", error)),
-        }
+        let items = ErpItemCatalogService::new(self.db_pool.clone())
+            .items(connection)
+            .await?;
+
+        Ok(items.into_iter().map(|item| ErpInventoryItem {
+            id: item.erp_item_id,
+            name: item.name,
+            description: item.description,
+            quantity: item.quantity,
+            custom_fields: item.custom_fields.0,
+        }).collect())
     }
 
     async fn save_mapping_suggestion(&self, connection_id: Uuid, suggestion: &MappingSuggestion) -> Result<()> {
@@ -0,0 +1,359 @@
+// ERP Conflict Service
+// Review queue for sync conflicts (erp_conflict_queue): lists open conflicts with the latest
+// AI suggestion, applies the chosen side to Atlas and/or the ERP, and undoes resolutions
+// within a short window
+
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::middleware::error_handling::{AppError, Result};
+use crate::services::erp::ErpSyncService;
+
+/// Minutes after a resolution during which it can be undone
+pub const UNDO_WINDOW_MINUTES: i64 = 30;
+
+// ============================================================================
+// Data Models
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct ConflictQueueItem {
+    pub id: Uuid,
+    pub erp_connection_id: Uuid,
+    pub erp_mapping_id: Uuid,
+    pub atlas_inventory_id: Uuid,
+    pub erp_item_id: String,
+    pub erp_location_id: Option<String>,
+    pub conflict_type: String,
+    pub atlas_value: Value,
+    pub erp_value: Value,
+    pub priority: String,
+    pub status: String,
+    pub detected_at: DateTime<Utc>,
+    pub resolution_action: Option<String>,
+    pub resolution_notes: Option<String>,
+    pub resolved_by: Option<Uuid>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub applied_values: Option<Value>,
+    pub undo_expires_at: Option<DateTime<Utc>>,
+    // Latest AI suggestion for the item, if any
+    pub ai_resolution_id: Option<Uuid>,
+    pub ai_suggested_resolution: Option<String>,
+    pub ai_reasoning: Option<String>,
+    pub ai_confidence_score: Option<Decimal>,
+    pub ai_risk_level: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ResolveConflictRequest {
+    /// "use_atlas" (write the Atlas quantity to the ERP), "use_erp" (write the ERP
+    /// quantity to Atlas) or "ignore"
+    pub action: String,
+    pub notes: Option<String>,
+}
+
+/// What a resolution (or its undo) wrote, for the audit log
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ConflictResolutionOutcome {
+    pub conflict: ConflictQueueItem,
+    #[schema(value_type = Option<Vec<i32>>)]
+    pub atlas_quantity: Option<(i32, i32)>,
+    #[schema(value_type = Option<Vec<i32>>)]
+    pub erp_quantity: Option<(i32, i32)>,
+}
+
+/// Open conflicts, the AI suggestion not yet acted on for the same item
+const CONFLICT_QUERY: &str = r#"
+    SELECT q.id, q.erp_connection_id, q.erp_mapping_id, m.atlas_inventory_id, m.erp_item_id,
+           m.erp_location_id, q.conflict_type, q.atlas_value, q.erp_value, q.priority, q.status,
+           q.detected_at, q.resolution_action, q.resolution_notes, q.resolved_by, q.resolved_at,
+           q.applied_values, q.undo_expires_at,
+           ai.id AS ai_resolution_id,
+           ai.ai_suggested_resolution, ai.ai_reasoning,
+           ai.confidence_score AS ai_confidence_score, ai.risk_level AS ai_risk_level
+    FROM erp_conflict_queue q
+    JOIN erp_inventory_mappings m ON m.id = q.erp_mapping_id
+    LEFT JOIN LATERAL (
+        SELECT r.id, r.ai_suggested_resolution, r.ai_reasoning, r.confidence_score, r.risk_level
+        FROM erp_ai_conflict_resolutions r
+        WHERE r.id = q.ai_resolution_id
+           OR (q.ai_resolution_id IS NULL
+               AND r.erp_connection_id = q.erp_connection_id
+               AND r.atlas_inventory_id = m.atlas_inventory_id
+               AND r.erp_item_id = m.erp_item_id
+               AND r.resolved_at IS NULL)
+        ORDER BY r.created_at DESC
+        LIMIT 1
+    ) ai ON true
+"#;
+
+// ============================================================================
+// ERP Conflict Service
+// ============================================================================
+
+pub struct ErpConflictService {
+    db_pool: PgPool,
+    sync_service: ErpSyncService,
+}
+
+impl ErpConflictService {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self {
+            sync_service: ErpSyncService::new(db_pool.clone()),
+            db_pool,
+        }
+    }
+
+    /// Conflicts of a connection, newest first. `status` is pending, resolved, ignored,
+    /// auto_resolved, or None for all.
+    pub async fn list_conflicts(&self, connection_id: Uuid, status: Option<&str>) -> Result<Vec<ConflictQueueItem>> {
+        let conflicts = sqlx::query_as::<_, ConflictQueueItem>(&format!(
+            "{} WHERE q.erp_connection_id = $1 AND ($2::text IS NULL OR q.status = $2) \
+             ORDER BY q.detected_at DESC LIMIT 200",
+            CONFLICT_QUERY
+        ))
+        .bind(connection_id)
+        .bind(status)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(conflicts)
+    }
+
+    /// A conflict on one of the user's connections
+    pub async fn get_conflict(&self, conflict_id: Uuid, user_id: Uuid) -> Result<ConflictQueueItem> {
+        let owner: Option<Uuid> = sqlx::query_scalar(
+            r#"
+            SELECT c.user_id
+            FROM erp_conflict_queue q
+            JOIN erp_connections c ON c.id = q.erp_connection_id
+            WHERE q.id = $1
+            "#
+        )
+        .bind(conflict_id)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        match owner {
+            None => return Err(AppError::NotFound("Conflict not found".to_string())),
+            Some(owner) if owner != user_id => {
+                return Err(AppError::Forbidden(
+                    "You don't have permission to access this conflict".to_string(),
+                ));
+            }
+            Some(_) => {}
+        }
+
+        self.load_conflict(conflict_id).await
+    }
+
+    /// Apply the chosen side of a pending conflict. The replaced quantities are kept so the
+    /// resolution can be undone for `UNDO_WINDOW_MINUTES`.
+    pub async fn resolve_conflict(
+        &self,
+        conflict_id: Uuid,
+        user_id: Uuid,
+        request: &ResolveConflictRequest,
+    ) -> Result<ConflictResolutionOutcome> {
+        let conflict = self.get_conflict(conflict_id, user_id).await?;
+
+        // The row stays locked while the chosen side is written, so a conflict is applied once
+        let mut tx = self.db_pool.begin().await?;
+        let status = lock_conflict(&mut tx, conflict_id).await?;
+        if status != "pending" {
+            return Err(AppError::BadRequest(format!("Conflict is already {}", status)));
+        }
+
+        let atlas_current = self.atlas_quantity(conflict.atlas_inventory_id).await?;
+        let erp_current = quantity(&conflict.erp_value);
+
+        let (status, ai_resolution, atlas_quantity, erp_quantity) = match request.action.as_str() {
+            "use_atlas" => {
+                let before = erp_current.ok_or_else(|| {
+                    AppError::BadRequest("The conflict has no recorded ERP quantity; run a sync to refresh it".to_string())
+                })?;
+                self.sync_service
+                    .write_erp_quantity(conflict.erp_mapping_id, atlas_current)
+                    .await
+                    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to write to ERP: {}", e)))?;
+                ("resolved", "atlas_wins", None, Some((before, atlas_current)))
+            }
+            "use_erp" => {
+                let erp_quantity = erp_current.ok_or_else(|| {
+                    AppError::BadRequest("The conflict has no recorded ERP quantity; run a sync to refresh it".to_string())
+                })?;
+                self.sync_service
+                    .write_atlas_quantity(conflict.erp_mapping_id, erp_quantity)
+                    .await
+                    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to update Atlas inventory: {}", e)))?;
+                ("resolved", "erp_wins", Some((atlas_current, erp_quantity)), None)
+            }
+            "ignore" => ("ignored", "reject_sync", None, None),
+            other => {
+                return Err(AppError::BadRequest(format!(
+                    "Invalid action '{}': expected use_atlas, use_erp or ignore",
+                    other
+                )));
+            }
+        };
+
+        let applied_values = serde_json::json!({
+            "atlas_quantity": atlas_quantity.map(|(before, after)| serde_json::json!({ "before": before, "after": after })),
+            "erp_quantity": erp_quantity.map(|(before, after)| serde_json::json!({ "before": before, "after": after })),
+        });
+
+        sqlx::query(
+            r#"
+            UPDATE erp_conflict_queue
+            SET status = $2, resolution_action = $3, resolution_notes = $4, resolved_by = $5,
+                resolved_at = NOW(), applied_values = $6, undo_expires_at = $7, ai_resolution_id = $8
+            WHERE id = $1
+            "#
+        )
+        .bind(conflict_id)
+        .bind(status)
+        .bind(&request.action)
+        .bind(request.notes.as_deref().map(str::trim).filter(|n| !n.is_empty()))
+        .bind(user_id)
+        .bind(&applied_values)
+        .bind(Utc::now() + Duration::minutes(UNDO_WINDOW_MINUTES))
+        .bind(conflict.ai_resolution_id)
+        .execute(&mut *tx)
+        .await?;
+
+        if let Some(ai_resolution_id) = conflict.ai_resolution_id {
+            sqlx::query(
+                r#"
+                UPDATE erp_ai_conflict_resolutions
+                SET resolution_taken = $2, resolved_by = $3, resolved_at = NOW(),
+                    resolution_notes = $4, updated_at = NOW()
+                WHERE id = $1
+                "#
+            )
+            .bind(ai_resolution_id)
+            .bind(ai_resolution)
+            .bind(user_id)
+            .bind(&request.notes)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(ConflictResolutionOutcome {
+            conflict: self.load_conflict(conflict_id).await?,
+            atlas_quantity,
+            erp_quantity,
+        })
+    }
+
+    /// Restore the quantities a resolution replaced and reopen the conflict
+    pub async fn undo_resolution(&self, conflict_id: Uuid, user_id: Uuid) -> Result<ConflictResolutionOutcome> {
+        let conflict = self.get_conflict(conflict_id, user_id).await?;
+
+        let mut tx = self.db_pool.begin().await?;
+        let status = lock_conflict(&mut tx, conflict_id).await?;
+
+        let within_window = conflict.undo_expires_at.map(|t| t > Utc::now()).unwrap_or(false);
+        if status != conflict.status || !matches!(status.as_str(), "resolved" | "ignored") || !within_window {
+            return Err(AppError::BadRequest(format!(
+                "Only resolutions from the last {} minutes can be undone",
+                UNDO_WINDOW_MINUTES
+            )));
+        }
+
+        let applied = conflict.applied_values.clone().unwrap_or(Value::Null);
+        let atlas_quantity = before_after(&applied["atlas_quantity"]);
+        let erp_quantity = before_after(&applied["erp_quantity"]);
+
+        // Restore in the opposite direction of the resolution
+        if let Some((before, _)) = atlas_quantity {
+            self.sync_service
+                .write_atlas_quantity(conflict.erp_mapping_id, before)
+                .await
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to update Atlas inventory: {}", e)))?;
+        }
+        if let Some((before, _)) = erp_quantity {
+            self.sync_service
+                .write_erp_quantity(conflict.erp_mapping_id, before)
+                .await
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to write to ERP: {}", e)))?;
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE erp_conflict_queue
+            SET status = 'pending', resolution_action = NULL, resolution_notes = NULL, resolved_by = NULL,
+                resolved_at = NULL, applied_values = NULL, undo_expires_at = NULL, ai_resolution_id = NULL
+            WHERE id = $1
+            "#
+        )
+        .bind(conflict_id)
+        .execute(&mut *tx)
+        .await?;
+
+        if let Some(ai_resolution_id) = conflict.ai_resolution_id {
+            sqlx::query(
+                r#"
+                UPDATE erp_ai_conflict_resolutions
+                SET resolution_taken = NULL, resolved_by = NULL, resolved_at = NULL,
+                    resolution_notes = NULL, updated_at = NOW()
+                WHERE id = $1
+                "#
+            )
+            .bind(ai_resolution_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        // Report the undo as the reverse change
+        Ok(ConflictResolutionOutcome {
+            conflict: self.load_conflict(conflict_id).await?,
+            atlas_quantity: atlas_quantity.map(|(before, after)| (after, before)),
+            erp_quantity: erp_quantity.map(|(before, after)| (after, before)),
+        })
+    }
+
+    async fn load_conflict(&self, conflict_id: Uuid) -> Result<ConflictQueueItem> {
+        sqlx::query_as::<_, ConflictQueueItem>(&format!("{} WHERE q.id = $1", CONFLICT_QUERY))
+            .bind(conflict_id)
+            .fetch_optional(&self.db_pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Conflict not found".to_string()))
+    }
+
+    async fn atlas_quantity(&self, inventory_id: Uuid) -> Result<i32> {
+        sqlx::query_scalar("SELECT quantity FROM inventory WHERE id = $1")
+            .bind(inventory_id)
+            .fetch_optional(&self.db_pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Inventory for this conflict no longer exists".to_string()))
+    }
+}
+
+async fn lock_conflict(tx: &mut sqlx::Transaction<'_, sqlx::Postgres>, conflict_id: Uuid) -> Result<String> {
+    sqlx::query_scalar("SELECT status FROM erp_conflict_queue WHERE id = $1 FOR UPDATE")
+        .bind(conflict_id)
+        .fetch_optional(&mut **tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Conflict not found".to_string()))
+}
+
+/// `{"quantity": n}` as recorded by the sync service
+fn quantity(value: &Value) -> Option<i32> {
+    value.get("quantity")?.as_i64().map(|q| q as i32)
+}
+
+fn before_after(value: &Value) -> Option<(i32, i32)> {
+    Some((
+        value.get("before")?.as_i64()? as i32,
+        value.get("after")?.as_i64()? as i32,
+    ))
+}
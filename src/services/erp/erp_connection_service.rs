@@ -5,11 +5,16 @@
 use sqlx::PgPool;
 use uuid::Uuid;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use chrono::{DateTime, Utc};
 use thiserror::Error;
 
 use crate::services::encryption_service::EncryptionService;
-use crate::services::erp::{NetSuiteClient, NetSuiteConfig, SapClient, SapConfig, SapEnvironment};
+use crate::services::erp::{
+    NetSuiteClient, NetSuiteConfig, NetSuiteError, SapClient, SapConfig, SapEnvironment, SapError, SftpClient, SftpConfig,
+};
+use crate::services::erp::flat_file_format::FlatFileColumnMapping;
+use crate::services::erp::api_guard::{self, CircuitStatus};
 
 // ============================================================================
 // Error Types
@@ -40,6 +45,12 @@ pub enum ErpConnectionError {
 
     #[error("SAP error: {0}")]
     SapError(String),
+
+    #[error("SFTP error: {0}")]
+    SftpError(String),
+
+    #[error("ERP item not found: {0}")]
+    ErpItemNotFound(String),
 }
 
 pub type Result<T> = std::result::Result<T, ErpConnectionError>;
@@ -48,13 +59,15 @@ pub type Result<T> = std::result::Result<T, ErpConnectionError>;
 // Data Models
 // ============================================================================
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum ErpType {
     #[serde(rename = "netsuite")]
     NetSuite,
     #[serde(rename = "sap_s4hana")]
     SapS4Hana,
+    #[serde(rename = "flat_file")]
+    FlatFile,
 }
 
 impl ErpType {
@@ -62,6 +75,7 @@ impl ErpType {
         match self {
             ErpType::NetSuite => "netsuite",
             ErpType::SapS4Hana => "sap_s4hana",
+            ErpType::FlatFile => "flat_file",
         }
     }
 
@@ -69,6 +83,7 @@ impl ErpType {
         match s {
             "netsuite" => Ok(ErpType::NetSuite),
             "sap_s4hana" => Ok(ErpType::SapS4Hana),
+            "flat_file" => Ok(ErpType::FlatFile),
             _ => Err(ErpConnectionError::InvalidErpType(s.to_string())),
         }
     }
@@ -88,6 +103,10 @@ pub struct ErpConnection {
     // SAP credentials (decrypted in memory)
     pub sap_config: Option<SapConfig>,
 
+    // Flat-file SFTP credentials (decrypted in memory) and CSV layout
+    pub flat_file_config: Option<SftpConfig>,
+    pub column_mapping: Option<FlatFileColumnMapping>,
+
     // Sync configuration
     pub sync_enabled: bool,
     pub sync_frequency_minutes: i32,
@@ -104,12 +123,29 @@ pub struct ErpConnection {
     pub default_sync_direction: SyncDirection,
     pub conflict_resolution: ConflictResolution,
 
+    /// ERP writes are captured in erp_sandbox_writes instead of executed
+    pub sandbox_mode: bool,
+
+    // Marketplace order export
+    pub export_sales_orders: bool,
+    pub export_purchase_orders: bool,
+    pub order_customer_id: Option<String>,
+    pub order_vendor_id: Option<String>,
+
+    // Delta sync cursor (see ErpSyncService::sync_from_erp_to_atlas)
+    pub delta_token: Option<String>,
+    pub delta_token_updated_at: Option<DateTime<Utc>>,
+
+    /// Subsidiaries (NetSuite) or plants (SAP) synced by this connection; empty means
+    /// the single configured plant / default location
+    pub entities: Vec<ErpEntity>,
+
     // Metadata
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum ConnectionStatus {
     Active,
@@ -190,6 +226,19 @@ pub struct CreateConnectionRequest {
     pub sap_plant: Option<String>,
     pub sap_company_code: Option<String>,
 
+    // Flat-file (CSV over SFTP) fields
+    pub sftp_host: Option<String>,
+    pub sftp_port: Option<i32>,
+    pub sftp_username: Option<String>,
+    pub sftp_password: Option<String>,
+    pub sftp_private_key: Option<String>,
+    pub sftp_host_key_fingerprint: Option<String>,
+    pub sftp_inbound_path: Option<String>,
+    pub sftp_outbound_path: Option<String>,
+    pub sftp_archive_path: Option<String>,
+    pub sftp_file_pattern: Option<String>,
+    pub csv_column_mapping: Option<FlatFileColumnMapping>,
+
     // Sync configuration
     pub sync_enabled: Option<bool>,
     pub sync_frequency_minutes: Option<i32>,
@@ -199,26 +248,121 @@ pub struct CreateConnectionRequest {
     pub sync_lot_batch: Option<bool>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ConnectionResponse {
     pub id: Uuid,
     pub erp_type: ErpType,
     pub connection_name: String,
     pub status: ConnectionStatus,
     pub sync_enabled: bool,
+    pub sandbox_mode: bool,
+    /// ERP API circuit breaker; syncs pause while it is open
+    pub circuit: CircuitStatus,
     pub last_sync_at: Option<DateTime<Utc>>,
     pub last_sync_status: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ConnectionTestResult {
     pub success: bool,
     pub message: String,
     pub details: Option<serde_json::Value>,
 }
 
+/// An ERP item as fetched when validating a mapping
+#[derive(Debug, Clone, Serialize)]
+pub struct ErpItemDetails {
+    pub item_name: Option<String>,
+    pub location_name: Option<String>,
+    /// Raw ERP record, cached on the mapping
+    pub snapshot: Option<serde_json::Value>,
+}
+
+/// A NetSuite subsidiary or SAP plant synced by a connection
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct ErpEntity {
+    pub id: Uuid,
+    /// "subsidiary" (NetSuite) or "plant" (SAP)
+    pub entity_type: String,
+    pub entity_id: String,
+    pub entity_name: Option<String>,
+    pub company_code: Option<String>,
+    pub default_location_id: Option<String>,
+    pub atlas_storage_location: Option<String>,
+    pub sync_enabled: bool,
+}
+
+/// One entity in a connection's entity list (PUT replaces the whole list)
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct ErpEntityInput {
+    pub entity_id: String,
+    pub entity_name: Option<String>,
+    pub company_code: Option<String>,
+    pub default_location_id: Option<String>,
+    pub atlas_storage_location: Option<String>,
+    pub sync_enabled: Option<bool>,
+}
+
+impl ErpConnection {
+    /// Entities with sync enabled
+    pub fn enabled_entities(&self) -> impl Iterator<Item = &ErpEntity> {
+        self.entities.iter().filter(|entity| entity.sync_enabled)
+    }
+
+    pub fn entity(&self, entity_id: &str) -> Option<&ErpEntity> {
+        self.entities.iter().find(|entity| entity.entity_id == entity_id)
+    }
+
+    /// The enabled entity serving an Atlas storage location, falling back to the only
+    /// enabled entity when there is exactly one
+    pub fn entity_for_storage_location(&self, storage_location: Option<&str>) -> Option<&ErpEntity> {
+        if let Some(location) = storage_location {
+            if let Some(entity) = self
+                .enabled_entities()
+                .find(|entity| entity.atlas_storage_location.as_deref() == Some(location))
+            {
+                return Some(entity);
+            }
+        }
+
+        let mut enabled = self.enabled_entities();
+        match (enabled.next(), enabled.next()) {
+            (Some(only), None) => Some(only),
+            _ => None,
+        }
+    }
+
+    /// SAP plant a mapping syncs against: its entity, else the configured plant
+    pub fn sap_plant_for(&self, erp_entity_id: Option<&str>) -> String {
+        erp_entity_id
+            .map(str::to_string)
+            .or_else(|| self.sap_config.as_ref().and_then(|c| c.plant.clone()))
+            .unwrap_or_else(|| "1000".to_string())
+    }
+
+    /// SAP plants read by an ERP -> Atlas sync
+    pub fn sap_plants(&self) -> Vec<String> {
+        let plants: Vec<String> = self.enabled_entities().map(|e| e.entity_id.clone()).collect();
+        if plants.is_empty() {
+            vec![self.sap_plant_for(None)]
+        } else {
+            plants
+        }
+    }
+
+    /// IDs recorded on sync logs; `None` when the connection has no entities
+    pub fn sync_entity_ids(&self) -> Option<Vec<String>> {
+        let ids: Vec<String> = self.enabled_entities().map(|e| e.entity_id.clone()).collect();
+        if ids.is_empty() {
+            None
+        } else {
+            Some(ids)
+        }
+    }
+}
+
 // ============================================================================
 // ERP Connection Service
 // ============================================================================
@@ -266,6 +410,10 @@ impl ErpConnectionService {
                 self.create_sap_connection(connection_id, user_id, request, now)
                     .await
             }
+            ErpType::FlatFile => {
+                self.create_flat_file_connection(connection_id, user_id, request, now)
+                    .await
+            }
         }
     }
 
@@ -419,6 +567,91 @@ impl ErpConnectionService {
         self.get_connection_by_id(connection_id).await
     }
 
+    async fn create_flat_file_connection(
+        &self,
+        connection_id: Uuid,
+        user_id: Uuid,
+        request: CreateConnectionRequest,
+        now: DateTime<Utc>,
+    ) -> Result<ErpConnection> {
+        let host = request.sftp_host.as_ref()
+            .ok_or_else(|| ErpConnectionError::ConfigError("sftp_host is required".to_string()))?;
+        let username = request.sftp_username.as_ref()
+            .ok_or_else(|| ErpConnectionError::ConfigError("sftp_username is required".to_string()))?;
+        let inbound_path = request.sftp_inbound_path.as_ref()
+            .ok_or_else(|| ErpConnectionError::ConfigError("sftp_inbound_path is required".to_string()))?;
+        let outbound_path = request.sftp_outbound_path.as_ref()
+            .ok_or_else(|| ErpConnectionError::ConfigError("sftp_outbound_path is required".to_string()))?;
+
+        // Encrypt credentials
+        let encrypted_username = self.encryption_service.encrypt(username)
+            .map_err(|e| ErpConnectionError::EncryptionError(e.to_string()))?;
+        let encrypted_password = request.sftp_password.as_deref()
+            .map(|password| self.encryption_service.encrypt(password))
+            .transpose()
+            .map_err(|e| ErpConnectionError::EncryptionError(e.to_string()))?;
+        let encrypted_private_key = request.sftp_private_key.as_deref()
+            .map(|key| self.encryption_service.encrypt(key))
+            .transpose()
+            .map_err(|e| ErpConnectionError::EncryptionError(e.to_string()))?;
+
+        let column_mapping = request.csv_column_mapping.clone().unwrap_or_default();
+
+        sqlx::query(
+            r#"
+            INSERT INTO erp_connections (
+                id, user_id, erp_type, connection_name, status,
+                sftp_host, sftp_port, sftp_username, sftp_password, sftp_private_key,
+                sftp_host_key_fingerprint, sftp_inbound_path, sftp_outbound_path,
+                sftp_archive_path, sftp_file_pattern, csv_column_mapping,
+                sync_enabled, sync_frequency_minutes,
+                sync_stock_levels, sync_product_master, sync_transactions, sync_lot_batch,
+                default_sync_direction, conflict_resolution,
+                created_at, updated_at
+            ) VALUES (
+                $1, $2, $3, $4, $5,
+                $6, $7, $8, $9, $10,
+                $11, $12, $13,
+                $14, $15, $16,
+                $17, $18,
+                $19, $20, $21, $22,
+                $23, $24,
+                $25, $26
+            )
+            "#
+        )
+        .bind(connection_id)
+        .bind(user_id)
+        .bind(ErpType::FlatFile.as_str())
+        .bind(&request.connection_name)
+        .bind(ConnectionStatus::Active.as_str())
+        .bind(host)
+        .bind(request.sftp_port.unwrap_or(22))
+        .bind(encrypted_username)
+        .bind(encrypted_password)
+        .bind(encrypted_private_key)
+        .bind(&request.sftp_host_key_fingerprint)
+        .bind(inbound_path)
+        .bind(outbound_path)
+        .bind(&request.sftp_archive_path)
+        .bind(request.sftp_file_pattern.as_deref().unwrap_or("*.csv"))
+        .bind(sqlx::types::Json(&column_mapping))
+        .bind(request.sync_enabled.unwrap_or(true))
+        .bind(request.sync_frequency_minutes.unwrap_or(60))
+        .bind(request.sync_stock_levels.unwrap_or(true))
+        .bind(request.sync_product_master.unwrap_or(false))
+        .bind(request.sync_transactions.unwrap_or(false))
+        .bind(request.sync_lot_batch.unwrap_or(true))
+        .bind(SyncDirection::Bidirectional.as_str())
+        .bind(ConflictResolution::AtlasWins.as_str())
+        .bind(now)
+        .bind(now)
+        .execute(&self.db_pool)
+        .await?;
+
+        self.get_connection_by_id(connection_id).await
+    }
+
     /// Get connection by ID with decrypted credentials
     pub async fn get_connection_by_id(&self, connection_id: Uuid) -> Result<ErpConnection> {
         let row = sqlx::query(
@@ -429,9 +662,14 @@ impl ErpConnectionService {
                 netsuite_token_id, netsuite_token_secret, netsuite_realm,
                 sap_base_url, sap_client_id, sap_client_secret, sap_token_endpoint,
                 sap_environment, sap_plant, sap_company_code,
+                sftp_host, sftp_port, sftp_username, sftp_password, sftp_private_key,
+                sftp_host_key_fingerprint, sftp_inbound_path, sftp_outbound_path,
+                sftp_archive_path, sftp_file_pattern, csv_column_mapping,
                 sync_enabled, sync_frequency_minutes, last_sync_at, last_sync_status,
                 sync_stock_levels, sync_product_master, sync_transactions, sync_lot_batch,
-                default_sync_direction, conflict_resolution,
+                default_sync_direction, conflict_resolution, sandbox_mode,
+                export_sales_orders, export_purchase_orders, order_customer_id, order_vendor_id,
+                delta_token, delta_token_updated_at,
                 created_at, updated_at
             FROM erp_connections
             WHERE id = $1
@@ -455,9 +693,14 @@ impl ErpConnectionService {
                 netsuite_token_id, netsuite_token_secret, netsuite_realm,
                 sap_base_url, sap_client_id, sap_client_secret, sap_token_endpoint,
                 sap_environment, sap_plant, sap_company_code,
+                sftp_host, sftp_port, sftp_username, sftp_password, sftp_private_key,
+                sftp_host_key_fingerprint, sftp_inbound_path, sftp_outbound_path,
+                sftp_archive_path, sftp_file_pattern, csv_column_mapping,
                 sync_enabled, sync_frequency_minutes, last_sync_at, last_sync_status,
                 sync_stock_levels, sync_product_master, sync_transactions, sync_lot_batch,
-                default_sync_direction, conflict_resolution,
+                default_sync_direction, conflict_resolution, sandbox_mode,
+                export_sales_orders, export_purchase_orders, order_customer_id, order_vendor_id,
+                delta_token, delta_token_updated_at,
                 created_at, updated_at
             FROM erp_connections
             WHERE user_id = $1
@@ -486,9 +729,14 @@ impl ErpConnectionService {
                 netsuite_token_id, netsuite_token_secret, netsuite_realm,
                 sap_base_url, sap_client_id, sap_client_secret, sap_token_endpoint,
                 sap_environment, sap_plant, sap_company_code,
+                sftp_host, sftp_port, sftp_username, sftp_password, sftp_private_key,
+                sftp_host_key_fingerprint, sftp_inbound_path, sftp_outbound_path,
+                sftp_archive_path, sftp_file_pattern, csv_column_mapping,
                 sync_enabled, sync_frequency_minutes, last_sync_at, last_sync_status,
                 sync_stock_levels, sync_product_master, sync_transactions, sync_lot_batch,
-                default_sync_direction, conflict_resolution,
+                default_sync_direction, conflict_resolution, sandbox_mode,
+                export_sales_orders, export_purchase_orders, order_customer_id, order_vendor_id,
+                delta_token, delta_token_updated_at,
                 created_at, updated_at
             FROM erp_connections
             WHERE user_id = $1 AND status = 'active' AND sync_enabled = true
@@ -573,6 +821,339 @@ impl ErpConnectionService {
         Ok(())
     }
 
+    /// Replace the CSV column mapping of a flat-file connection
+    pub async fn update_column_mapping(
+        &self,
+        connection_id: Uuid,
+        mapping: &FlatFileColumnMapping,
+    ) -> Result<()> {
+        mapping.validate().map_err(ErpConnectionError::ConfigError)?;
+
+        let result = sqlx::query(
+            r#"
+            UPDATE erp_connections
+            SET csv_column_mapping = $2, updated_at = NOW()
+            WHERE id = $1 AND erp_type = 'flat_file'
+            "#
+        )
+        .bind(connection_id)
+        .bind(sqlx::types::Json(mapping))
+        .execute(&self.db_pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(ErpConnectionError::NotFound(connection_id));
+        }
+
+        Ok(())
+    }
+
+    /// Turn sandbox mode on or off. Sandbox syncs read from the ERP but only capture their writes.
+    pub async fn set_sandbox_mode(&self, connection_id: Uuid, enabled: bool) -> Result<()> {
+        let result = sqlx::query(
+            "UPDATE erp_connections SET sandbox_mode = $2, updated_at = NOW() WHERE id = $1"
+        )
+        .bind(connection_id)
+        .bind(enabled)
+        .execute(&self.db_pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(ErpConnectionError::NotFound(connection_id));
+        }
+
+        Ok(())
+    }
+
+    /// Configure which marketplace orders are exported to the ERP. Sales orders need the
+    /// customer record they are booked against, purchase orders the vendor record.
+    pub async fn update_order_export_settings(
+        &self,
+        connection_id: Uuid,
+        export_sales_orders: bool,
+        export_purchase_orders: bool,
+        order_customer_id: Option<String>,
+        order_vendor_id: Option<String>,
+    ) -> Result<()> {
+        let order_customer_id = order_customer_id.filter(|id| !id.trim().is_empty());
+        let order_vendor_id = order_vendor_id.filter(|id| !id.trim().is_empty());
+
+        if export_sales_orders && order_customer_id.is_none() {
+            return Err(ErpConnectionError::ConfigError(
+                "order_customer_id is required to export sales orders".to_string(),
+            ));
+        }
+        if export_purchase_orders && order_vendor_id.is_none() {
+            return Err(ErpConnectionError::ConfigError(
+                "order_vendor_id is required to export purchase orders".to_string(),
+            ));
+        }
+
+        let result = sqlx::query(
+            r#"
+            UPDATE erp_connections
+            SET export_sales_orders = $2,
+                export_purchase_orders = $3,
+                order_customer_id = $4,
+                order_vendor_id = $5,
+                updated_at = NOW()
+            WHERE id = $1 AND erp_type <> 'flat_file'
+            "#
+        )
+        .bind(connection_id)
+        .bind(export_sales_orders)
+        .bind(export_purchase_orders)
+        .bind(order_customer_id)
+        .bind(order_vendor_id)
+        .execute(&self.db_pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(ErpConnectionError::NotFound(connection_id));
+        }
+
+        Ok(())
+    }
+
+    /// Store the connection's delta sync cursor; `None` forces the next ERP -> Atlas sync
+    /// to be a full sync
+    pub async fn set_delta_token(&self, connection_id: Uuid, token: Option<&str>) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE erp_connections
+            SET delta_token = $2,
+                delta_token_updated_at = CASE WHEN $2::text IS NULL THEN NULL ELSE NOW() END
+            WHERE id = $1
+            "#
+        )
+        .bind(connection_id)
+        .bind(token)
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Subsidiaries / plants of a connection
+    pub async fn get_entities(&self, connection_id: Uuid) -> Result<Vec<ErpEntity>> {
+        let entities = sqlx::query_as::<_, ErpEntity>(
+            r#"
+            SELECT id, entity_type, entity_id, entity_name, company_code, default_location_id,
+                   atlas_storage_location, sync_enabled
+            FROM erp_connection_entities
+            WHERE erp_connection_id = $1
+            ORDER BY created_at, entity_id
+            "#
+        )
+        .bind(connection_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(entities)
+    }
+
+    /// Replace a connection's subsidiaries (NetSuite) or plants (SAP). Mappings of removed
+    /// entities fall back to the connection default, and the delta cursor is reset since
+    /// it only covered the previous entities.
+    pub async fn replace_entities(
+        &self,
+        connection: &ErpConnection,
+        entities: Vec<ErpEntityInput>,
+    ) -> Result<Vec<ErpEntity>> {
+        let entity_type = match connection.erp_type {
+            ErpType::NetSuite => "subsidiary",
+            ErpType::SapS4Hana => "plant",
+            ErpType::FlatFile => {
+                return Err(ErpConnectionError::ConfigError(
+                    "Flat-file connections have no subsidiaries or plants".to_string(),
+                ));
+            }
+        };
+
+        let mut seen_ids = std::collections::HashSet::new();
+        let mut seen_locations = std::collections::HashSet::new();
+        for entity in &entities {
+            let entity_id = entity.entity_id.trim();
+            if entity_id.is_empty() || entity_id.len() > 100 {
+                return Err(ErpConnectionError::ConfigError("entity_id must be 1-100 characters".to_string()));
+            }
+            if !seen_ids.insert(entity_id.to_string()) {
+                return Err(ErpConnectionError::ConfigError(format!("Duplicate entity_id: {}", entity_id)));
+            }
+            if let Some(location) = entity.atlas_storage_location.as_deref().map(str::trim).filter(|l| !l.is_empty()) {
+                if !seen_locations.insert(location.to_string()) {
+                    return Err(ErpConnectionError::ConfigError(format!(
+                        "Storage location {} is mapped to more than one entity",
+                        location
+                    )));
+                }
+            }
+        }
+
+        let mut tx = self.db_pool.begin().await?;
+
+        let kept: Vec<String> = seen_ids.into_iter().collect();
+        sqlx::query(
+            "DELETE FROM erp_connection_entities WHERE erp_connection_id = $1 AND NOT (entity_id = ANY($2))"
+        )
+        .bind(connection.id)
+        .bind(&kept)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            UPDATE erp_inventory_mappings
+            SET erp_entity_id = NULL, updated_at = NOW()
+            WHERE erp_connection_id = $1 AND erp_entity_id IS NOT NULL AND NOT (erp_entity_id = ANY($2))
+            "#
+        )
+        .bind(connection.id)
+        .bind(&kept)
+        .execute(&mut *tx)
+        .await?;
+
+        // Clear storage locations first so swapping them between entities passes the unique index
+        sqlx::query("UPDATE erp_connection_entities SET atlas_storage_location = NULL WHERE erp_connection_id = $1")
+            .bind(connection.id)
+            .execute(&mut *tx)
+            .await?;
+
+        let trimmed = |value: Option<String>| value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+        for entity in entities {
+            sqlx::query(
+                r#"
+                INSERT INTO erp_connection_entities (
+                    erp_connection_id, entity_type, entity_id, entity_name, company_code,
+                    default_location_id, atlas_storage_location, sync_enabled
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                ON CONFLICT (erp_connection_id, entity_id) DO UPDATE SET
+                    entity_name = EXCLUDED.entity_name,
+                    company_code = EXCLUDED.company_code,
+                    default_location_id = EXCLUDED.default_location_id,
+                    atlas_storage_location = EXCLUDED.atlas_storage_location,
+                    sync_enabled = EXCLUDED.sync_enabled,
+                    updated_at = NOW()
+                "#
+            )
+            .bind(connection.id)
+            .bind(entity_type)
+            .bind(entity.entity_id.trim())
+            .bind(trimmed(entity.entity_name))
+            .bind(trimmed(entity.company_code))
+            .bind(trimmed(entity.default_location_id))
+            .bind(trimmed(entity.atlas_storage_location))
+            .bind(entity.sync_enabled.unwrap_or(true))
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        sqlx::query(
+            "UPDATE erp_connections SET delta_token = NULL, delta_token_updated_at = NULL, updated_at = NOW() WHERE id = $1"
+        )
+        .bind(connection.id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        self.get_entities(connection.id).await
+    }
+
+    /// Active flat-file connections whose sync frequency has elapsed since the last sync
+    /// Connections due for a scheduled sync: active, sync enabled, not running, past
+    /// their sync frequency and past any failure backoff
+    pub async fn get_due_connections(&self, limit: i64) -> Result<Vec<Uuid>> {
+        let ids = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            SELECT id
+            FROM erp_connections
+            WHERE sync_enabled = true
+              AND status = 'active'
+              -- Runs that never finished (e.g. a restart mid-sync) stop blocking after 2 hours
+              AND (last_sync_status IS DISTINCT FROM 'running'
+                   OR sync_started_at IS NULL
+                   OR sync_started_at < NOW() - INTERVAL '2 hours')
+              AND (last_sync_at IS NULL
+                   OR last_sync_at + make_interval(mins => sync_frequency_minutes) <= NOW())
+              AND (next_sync_at IS NULL OR next_sync_at <= NOW())
+            ORDER BY COALESCE(next_sync_at, last_sync_at) NULLS FIRST
+            LIMIT $1
+            "#
+        )
+        .bind(limit)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(ids)
+    }
+
+    /// Claim a connection for a sync run. Returns false when another sync of the
+    /// connection is already running, so each connection runs one sync at a time.
+    pub async fn try_start_sync(&self, connection_id: Uuid) -> Result<bool> {
+        let claimed = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            UPDATE erp_connections
+            SET last_sync_status = 'running', sync_started_at = NOW(), updated_at = NOW()
+            WHERE id = $1
+              AND (last_sync_status IS DISTINCT FROM 'running'
+                   OR sync_started_at IS NULL
+                   OR sync_started_at < NOW() - INTERVAL '2 hours')
+            RETURNING id
+            "#
+        )
+        .bind(connection_id)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        Ok(claimed.is_some())
+    }
+
+    /// Release the claim and record the outcome. Failed runs back off exponentially
+    /// (twice the sync frequency after the first failure, doubling up to 24 hours);
+    /// any run that completes resets the backoff. Runs interrupted by a shutdown leave
+    /// the last sync time and backoff as they were, so the connection is due again.
+    pub async fn finish_sync(
+        &self,
+        connection_id: Uuid,
+        status: &str,
+        duration_seconds: i32,
+        error: Option<&str>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE erp_connections
+            SET last_sync_at = CASE WHEN $2 = 'interrupted' THEN last_sync_at ELSE NOW() END,
+                last_sync_status = $2,
+                last_sync_duration_seconds = $3,
+                last_sync_error = $4,
+                sync_started_at = NULL,
+                consecutive_failures = CASE
+                    WHEN $2 = 'failed' THEN consecutive_failures + 1
+                    WHEN $2 = 'interrupted' THEN consecutive_failures
+                    ELSE 0
+                END,
+                next_sync_at = CASE
+                    WHEN $2 = 'failed' THEN NOW() + LEAST(
+                        make_interval(mins => sync_frequency_minutes) * power(2, LEAST(consecutive_failures + 1, 10)),
+                        INTERVAL '24 hours'
+                    )
+                    ELSE NULL
+                END,
+                updated_at = NOW()
+            WHERE id = $1
+            "#
+        )
+        .bind(connection_id)
+        .bind(status)
+        .bind(duration_seconds)
+        .bind(error)
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(())
+    }
+
     // ========================================================================
     // Connection Testing
     // ========================================================================
@@ -582,6 +1163,7 @@ impl ErpConnectionService {
         match &connection.erp_type {
             ErpType::NetSuite => self.test_netsuite_connection(connection).await,
             ErpType::SapS4Hana => self.test_sap_connection(connection).await,
+            ErpType::FlatFile => self.test_flat_file_connection(connection).await,
         }
     }
 
@@ -643,6 +1225,136 @@ impl ErpConnectionService {
         }
     }
 
+    async fn test_flat_file_connection(&self, connection: &ErpConnection) -> Result<ConnectionTestResult> {
+        let config = connection.flat_file_config.as_ref()
+            .ok_or_else(|| ErpConnectionError::ConfigError("SFTP config not loaded".to_string()))?;
+
+        let client = SftpClient::new(config.clone())
+            .map_err(|e| ErpConnectionError::SftpError(e.to_string()))?;
+
+        match client.list_inbound().await {
+            Ok(files) => Ok(ConnectionTestResult {
+                success: true,
+                message: "Successfully connected to SFTP server".to_string(),
+                details: Some(serde_json::json!({
+                    "host": config.host,
+                    "inbound_path": config.inbound_path,
+                    "pending_files": files.len(),
+                    "host_key_verified": config.host_key_fingerprint.is_some()
+                })),
+            }),
+            Err(e) => Ok(ConnectionTestResult {
+                success: false,
+                message: format!("Connection test failed: {}", e),
+                details: None,
+            }),
+        }
+    }
+
+    // ========================================================================
+    // ERP Item Lookup
+    // ========================================================================
+
+    /// Fetch an item (and location, if given) from the connection's ERP, to validate
+    /// a mapping before it is saved. Flat-file connections have no API to ask, so
+    /// their items are accepted as entered.
+    pub async fn lookup_erp_item(
+        &self,
+        connection: &ErpConnection,
+        erp_item_id: &str,
+        erp_location_id: Option<&str>,
+    ) -> Result<ErpItemDetails> {
+        match &connection.erp_type {
+            ErpType::NetSuite => self.lookup_netsuite_item(connection, erp_item_id, erp_location_id).await,
+            ErpType::SapS4Hana => self.lookup_sap_item(connection, erp_item_id, erp_location_id).await,
+            ErpType::FlatFile => Ok(ErpItemDetails {
+                item_name: None,
+                location_name: None,
+                snapshot: None,
+            }),
+        }
+    }
+
+    async fn lookup_netsuite_item(
+        &self,
+        connection: &ErpConnection,
+        erp_item_id: &str,
+        erp_location_id: Option<&str>,
+    ) -> Result<ErpItemDetails> {
+        let config = connection.netsuite_config.as_ref()
+            .ok_or_else(|| ErpConnectionError::ConfigError("NetSuite config not loaded".to_string()))?;
+
+        let client = NetSuiteClient::new(config.clone())
+            .map_err(|e| ErpConnectionError::NetSuiteError(e.to_string()))?;
+
+        let item = client.get_inventory_item(erp_item_id).await.map_err(|e| match e {
+            NetSuiteError::NotFound(_) => ErpConnectionError::ErpItemNotFound(format!("NetSuite item {}", erp_item_id)),
+            _ => ErpConnectionError::NetSuiteError(e.to_string()),
+        })?;
+
+        let location_name = match erp_location_id {
+            Some(location_id) => {
+                let location = item.locations.as_ref()
+                    .and_then(|locations| locations.items.iter().find(|l| l.location.id == location_id))
+                    .ok_or_else(|| ErpConnectionError::ErpItemNotFound(
+                        format!("NetSuite item {} at location {}", erp_item_id, location_id)
+                    ))?;
+                location.location.name.clone()
+            }
+            None => None,
+        };
+
+        Ok(ErpItemDetails {
+            item_name: Some(item.display_name.clone()),
+            location_name,
+            snapshot: serde_json::to_value(&item).ok(),
+        })
+    }
+
+    async fn lookup_sap_item(
+        &self,
+        connection: &ErpConnection,
+        erp_item_id: &str,
+        erp_location_id: Option<&str>,
+    ) -> Result<ErpItemDetails> {
+        let config = connection.sap_config.as_ref()
+            .ok_or_else(|| ErpConnectionError::ConfigError("SAP config not loaded".to_string()))?;
+
+        let client = SapClient::new(config.clone())
+            .map_err(|e| ErpConnectionError::SapError(e.to_string()))?;
+
+        let not_found = |e: SapError, what: String| match e {
+            SapError::NotFound(_) => ErpConnectionError::ErpItemNotFound(what),
+            _ => ErpConnectionError::SapError(e.to_string()),
+        };
+
+        let product = client.get_product(erp_item_id).await
+            .map_err(|e| not_found(e, format!("SAP material {}", erp_item_id)))?;
+
+        let mut snapshot = serde_json::json!({
+            "material": product.product,
+            "product_type": product.product_type,
+            "description": product.description,
+            "base_unit": product.base_unit,
+            "product_group": product.product_group,
+            "manufacturer": product.manufacturer,
+        });
+
+        // Same plant default as the sync
+        if let Some(storage_location) = erp_location_id {
+            let plant = config.plant.as_deref().unwrap_or("1000");
+            let stock = client.get_material_stock(erp_item_id, plant, storage_location).await
+                .map_err(|e| not_found(e, format!("SAP material {} in storage location {}", erp_item_id, storage_location)))?;
+            snapshot["stock"] = serde_json::to_value(&stock).unwrap_or_default();
+        }
+
+        Ok(ErpItemDetails {
+            item_name: product.description,
+            location_name: None,
+            snapshot: Some(snapshot),
+        })
+    }
+
     // ========================================================================
     // Helper Methods
     // ========================================================================
@@ -680,6 +1392,7 @@ impl ErpConnectionService {
                 token_id,
                 token_secret,
                 realm,
+                connection_id: Some(id),
             })
         } else {
             None
@@ -714,11 +1427,54 @@ impl ErpConnectionService {
                 environment,
                 plant,
                 company_code,
+                connection_id: Some(id),
+            })
+        } else {
+            None
+        };
+
+        let flat_file_config = if erp_type == ErpType::FlatFile {
+            let encrypted_username: String = row.get("sftp_username");
+            let encrypted_password: Option<String> = row.get("sftp_password");
+            let encrypted_private_key: Option<String> = row.get("sftp_private_key");
+            let port: Option<i32> = row.get("sftp_port");
+            let file_pattern: Option<String> = row.get("sftp_file_pattern");
+
+            // Decrypt credentials
+            let username = self.encryption_service.decrypt(&encrypted_username)
+                .map_err(|e| ErpConnectionError::EncryptionError(e.to_string()))?;
+            let password = encrypted_password
+                .map(|password| self.encryption_service.decrypt(&password))
+                .transpose()
+                .map_err(|e| ErpConnectionError::EncryptionError(e.to_string()))?;
+            let private_key = encrypted_private_key
+                .map(|key| self.encryption_service.decrypt(&key))
+                .transpose()
+                .map_err(|e| ErpConnectionError::EncryptionError(e.to_string()))?;
+
+            Some(SftpConfig {
+                host: row.get("sftp_host"),
+                port: port.unwrap_or(22) as u16,
+                username,
+                password,
+                private_key,
+                host_key_fingerprint: row.get("sftp_host_key_fingerprint"),
+                inbound_path: row.get("sftp_inbound_path"),
+                outbound_path: row.get("sftp_outbound_path"),
+                archive_path: row.get("sftp_archive_path"),
+                file_pattern: file_pattern.unwrap_or_else(|| "*.csv".to_string()),
             })
         } else {
             None
         };
 
+        let column_mapping = if erp_type == ErpType::FlatFile {
+            let mapping: Option<sqlx::types::Json<FlatFileColumnMapping>> = row.get("csv_column_mapping");
+            Some(mapping.map(|m| m.0).unwrap_or_default())
+        } else {
+            None
+        };
+
         let status_str: String = row.get("status");
         let status = match status_str.as_str() {
             "active" => ConnectionStatus::Active,
@@ -743,6 +1499,8 @@ impl ErpConnectionService {
             _ => ConflictResolution::AtlasWins,
         };
 
+        let entities = self.get_entities(id).await?;
+
         Ok(ErpConnection {
             id,
             user_id,
@@ -751,6 +1509,8 @@ impl ErpConnectionService {
             status,
             netsuite_config,
             sap_config,
+            flat_file_config,
+            column_mapping,
             sync_enabled: row.get("sync_enabled"),
             sync_frequency_minutes: row.get("sync_frequency_minutes"),
             last_sync_at: row.get("last_sync_at"),
@@ -761,6 +1521,14 @@ impl ErpConnectionService {
             sync_lot_batch: row.get("sync_lot_batch"),
             default_sync_direction,
             conflict_resolution,
+            sandbox_mode: row.get("sandbox_mode"),
+            export_sales_orders: row.get("export_sales_orders"),
+            export_purchase_orders: row.get("export_purchase_orders"),
+            order_customer_id: row.get("order_customer_id"),
+            order_vendor_id: row.get("order_vendor_id"),
+            delta_token: row.get("delta_token"),
+            delta_token_updated_at: row.get("delta_token_updated_at"),
+            entities,
             created_at: row.get("created_at"),
             updated_at: row.get("updated_at"),
         })
@@ -803,6 +1571,28 @@ impl ErpConnectionService {
                     return Err(ErpConnectionError::ConfigError("sap_token_endpoint is required".to_string()));
                 }
             }
+            ErpType::FlatFile => {
+                if request.sftp_host.is_none() {
+                    return Err(ErpConnectionError::ConfigError("sftp_host is required".to_string()));
+                }
+                if request.sftp_username.is_none() {
+                    return Err(ErpConnectionError::ConfigError("sftp_username is required".to_string()));
+                }
+                if request.sftp_password.is_none() && request.sftp_private_key.is_none() {
+                    return Err(ErpConnectionError::ConfigError("sftp_password or sftp_private_key is required".to_string()));
+                }
+                if request.sftp_inbound_path.is_none() || request.sftp_outbound_path.is_none() {
+                    return Err(ErpConnectionError::ConfigError("sftp_inbound_path and sftp_outbound_path are required".to_string()));
+                }
+                if let Some(port) = request.sftp_port {
+                    if !(1..=65535).contains(&port) {
+                        return Err(ErpConnectionError::ConfigError("sftp_port must be between 1 and 65535".to_string()));
+                    }
+                }
+                if let Some(mapping) = &request.csv_column_mapping {
+                    mapping.validate().map_err(ErpConnectionError::ConfigError)?;
+                }
+            }
         }
 
         Ok(())
@@ -815,6 +1605,8 @@ impl ErpConnectionService {
             connection_name: connection.connection_name.clone(),
             status: connection.status.clone(),
             sync_enabled: connection.sync_enabled,
+            sandbox_mode: connection.sandbox_mode,
+            circuit: api_guard::circuit_status(connection.id),
             last_sync_at: connection.last_sync_at,
             last_sync_status: connection.last_sync_status.clone(),
             created_at: connection.created_at,
@@ -0,0 +1,368 @@
+// ERP Field Mapping Service
+// Reusable field mapping templates (system defaults, plus each user's own) and the
+// template assigned to each NetSuite/SAP connection
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use sqlx::types::Json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::middleware::error_handling::{AppError, Result};
+use crate::services::erp::field_mapping::ErpFieldMapping;
+use crate::services::erp::{ErpConnection, ErpType};
+
+// ============================================================================
+// Data Models
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct FieldMappingTemplate {
+    pub id: Uuid,
+    pub erp_type: String,
+    pub template_name: String,
+    pub description: Option<String>,
+    pub category: Option<String>,
+    #[schema(value_type = ErpFieldMapping)]
+    pub field_mappings: Json<ErpFieldMapping>,
+    pub is_default: Option<bool>,
+    pub is_public: Option<bool>,
+    pub usage_count: Option<i32>,
+    /// NULL for system templates
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SaveFieldMappingTemplateRequest {
+    pub erp_type: String,
+    pub template_name: String,
+    pub description: Option<String>,
+    pub field_mappings: ErpFieldMapping,
+}
+
+/// The template in effect for a connection and what it lacks for the connection's syncs
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ConnectionFieldMapping {
+    pub template: Option<FieldMappingTemplate>,
+    /// True when the connection has no template of its own and uses the system default
+    pub is_system_default: bool,
+    #[schema(value_type = Vec<String>)]
+    pub missing_fields: Vec<&'static str>,
+}
+
+const TEMPLATE_COLUMNS: &str = "id, erp_type, template_name, description, category, field_mappings, \
+    is_default, is_public, usage_count, created_by, created_at, updated_at";
+
+// ============================================================================
+// ERP Field Mapping Service
+// ============================================================================
+
+pub struct ErpFieldMappingService {
+    db_pool: PgPool,
+}
+
+impl ErpFieldMappingService {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self { db_pool }
+    }
+
+    // ========================================================================
+    // Templates
+    // ========================================================================
+
+    /// Public templates and the user's own, optionally for one ERP type
+    pub async fn list_templates(&self, user_id: Uuid, erp_type: Option<&str>) -> Result<Vec<FieldMappingTemplate>> {
+        let templates = sqlx::query_as::<_, FieldMappingTemplate>(&format!(
+            r#"
+            SELECT {}
+            FROM erp_field_mapping_templates
+            WHERE (is_public = true OR created_by = $1)
+              AND ($2::text IS NULL OR erp_type = $2)
+            ORDER BY erp_type, is_default DESC NULLS LAST, template_name
+            "#,
+            TEMPLATE_COLUMNS
+        ))
+        .bind(user_id)
+        .bind(erp_type)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(templates)
+    }
+
+    /// A template the user can see (public or their own)
+    pub async fn get_template(&self, template_id: Uuid, user_id: Uuid) -> Result<FieldMappingTemplate> {
+        sqlx::query_as::<_, FieldMappingTemplate>(&format!(
+            "SELECT {} FROM erp_field_mapping_templates WHERE id = $1 AND (is_public = true OR created_by = $2)",
+            TEMPLATE_COLUMNS
+        ))
+        .bind(template_id)
+        .bind(user_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Field mapping template not found".to_string()))
+    }
+
+    pub async fn create_template(
+        &self,
+        user_id: Uuid,
+        request: SaveFieldMappingTemplateRequest,
+    ) -> Result<FieldMappingTemplate> {
+        validate_template_request(&request)?;
+
+        sqlx::query_as::<_, FieldMappingTemplate>(&format!(
+            r#"
+            INSERT INTO erp_field_mapping_templates (
+                erp_type, template_name, description, category, field_mappings,
+                is_default, is_public, created_by
+            ) VALUES ($1, $2, $3, 'custom', $4, false, false, $5)
+            RETURNING {}
+            "#,
+            TEMPLATE_COLUMNS
+        ))
+        .bind(&request.erp_type)
+        .bind(request.template_name.trim())
+        .bind(&request.description)
+        .bind(Json(&request.field_mappings))
+        .bind(user_id)
+        .fetch_one(&self.db_pool)
+        .await
+        .map_err(conflict_on_duplicate_name)
+    }
+
+    /// Edit one of the user's own templates. Connections using it must stay complete.
+    pub async fn update_template(
+        &self,
+        template_id: Uuid,
+        user_id: Uuid,
+        request: SaveFieldMappingTemplateRequest,
+    ) -> Result<FieldMappingTemplate> {
+        validate_template_request(&request)?;
+
+        let existing = self.get_own_template(template_id, user_id).await?;
+        if existing.erp_type != request.erp_type {
+            return Err(AppError::BadRequest("The ERP type of a template cannot change".to_string()));
+        }
+
+        // Connections using the template must keep every field they sync
+        let (in_use, lot_batch): (i64, i64) = sqlx::query_as(
+            r#"
+            SELECT COUNT(*), COUNT(*) FILTER (WHERE sync_lot_batch = true)
+            FROM erp_connections
+            WHERE field_mapping_template_id = $1
+            "#
+        )
+        .bind(template_id)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        let missing = request.field_mappings.missing_for_sync(lot_batch > 0);
+        if in_use > 0 && !missing.is_empty() {
+            return Err(AppError::BadRequest(format!(
+                "Template is used by {} connection(s) and would be missing: {}",
+                in_use,
+                missing.join(", ")
+            )));
+        }
+
+        sqlx::query_as::<_, FieldMappingTemplate>(&format!(
+            r#"
+            UPDATE erp_field_mapping_templates
+            SET template_name = $2, description = $3, field_mappings = $4, updated_at = NOW()
+            WHERE id = $1
+            RETURNING {}
+            "#,
+            TEMPLATE_COLUMNS
+        ))
+        .bind(template_id)
+        .bind(request.template_name.trim())
+        .bind(&request.description)
+        .bind(Json(&request.field_mappings))
+        .fetch_one(&self.db_pool)
+        .await
+        .map_err(conflict_on_duplicate_name)
+    }
+
+    /// Delete one of the user's own templates; its connections fall back to the system default
+    pub async fn delete_template(&self, template_id: Uuid, user_id: Uuid) -> Result<()> {
+        self.get_own_template(template_id, user_id).await?;
+
+        sqlx::query("DELETE FROM erp_field_mapping_templates WHERE id = $1")
+            .bind(template_id)
+            .execute(&self.db_pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_own_template(&self, template_id: Uuid, user_id: Uuid) -> Result<FieldMappingTemplate> {
+        let template = self.get_template(template_id, user_id).await?;
+        if template.created_by != Some(user_id) {
+            return Err(AppError::Forbidden("System and shared templates cannot be modified".to_string()));
+        }
+        Ok(template)
+    }
+
+    // ========================================================================
+    // Connection Templates
+    // ========================================================================
+
+    /// The template in effect for a connection: its own, else the system default of its
+    /// ERP type. Flat-file connections use CSV column mappings instead and have none.
+    pub async fn template_for_connection(&self, connection: &ErpConnection) -> Result<Option<(FieldMappingTemplate, bool)>> {
+        self.template_for(connection.id, &connection.erp_type).await
+    }
+
+    /// The field mapping in effect for a connection, complete or not (webhooks, discovery)
+    pub async fn mapping_for(&self, connection_id: Uuid, erp_type: &ErpType) -> Result<ErpFieldMapping> {
+        Ok(self
+            .template_for(connection_id, erp_type)
+            .await?
+            .map(|(template, _)| template.field_mappings.0)
+            .unwrap_or_default())
+    }
+
+    async fn template_for(&self, connection_id: Uuid, erp_type: &ErpType) -> Result<Option<(FieldMappingTemplate, bool)>> {
+        if *erp_type == ErpType::FlatFile {
+            return Ok(None);
+        }
+
+        let assigned = sqlx::query_as::<_, FieldMappingTemplate>(&format!(
+            r#"
+            SELECT {}
+            FROM erp_field_mapping_templates
+            WHERE id = (SELECT field_mapping_template_id FROM erp_connections WHERE id = $1)
+            "#,
+            TEMPLATE_COLUMNS
+        ))
+        .bind(connection_id)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        if let Some(template) = assigned {
+            return Ok(Some((template, false)));
+        }
+
+        let default = sqlx::query_as::<_, FieldMappingTemplate>(&format!(
+            r#"
+            SELECT {}
+            FROM erp_field_mapping_templates
+            WHERE erp_type = $1 AND is_default = true AND created_by IS NULL
+            ORDER BY created_at
+            LIMIT 1
+            "#,
+            TEMPLATE_COLUMNS
+        ))
+        .bind(erp_type.as_str())
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        Ok(default.map(|template| (template, true)))
+    }
+
+    pub async fn describe_connection(&self, connection: &ErpConnection) -> Result<ConnectionFieldMapping> {
+        let template = self.template_for_connection(connection).await?;
+        let missing_fields = match (&template, &connection.erp_type) {
+            (_, ErpType::FlatFile) => Vec::new(),
+            (Some((template, _)), _) => template.field_mappings.missing_for_sync(connection.sync_lot_batch),
+            (None, _) => ErpFieldMapping::default().missing_for_sync(connection.sync_lot_batch),
+        };
+
+        Ok(ConnectionFieldMapping {
+            is_system_default: template.as_ref().map(|(_, default)| *default).unwrap_or(false),
+            template: template.map(|(template, _)| template),
+            missing_fields,
+        })
+    }
+
+    /// The connection's field mapping, refusing incomplete ones. Called before every sync.
+    pub async fn require_complete_mapping(&self, connection: &ErpConnection) -> Result<ErpFieldMapping> {
+        if connection.erp_type == ErpType::FlatFile {
+            return Ok(ErpFieldMapping::default());
+        }
+
+        let mapping = self.mapping_for(connection.id, &connection.erp_type).await?;
+
+        let missing = mapping.missing_for_sync(connection.sync_lot_batch);
+        if !missing.is_empty() {
+            return Err(AppError::BadRequest(format!(
+                "Field mapping template is incomplete, missing: {}",
+                missing.join(", ")
+            )));
+        }
+
+        Ok(mapping)
+    }
+
+    /// Assign a template to a connection (None = back to the system default)
+    pub async fn assign_template(&self, connection: &ErpConnection, template_id: Option<Uuid>) -> Result<ConnectionFieldMapping> {
+        if connection.erp_type == ErpType::FlatFile {
+            return Err(AppError::BadRequest(
+                "Flat-file connections use CSV column mappings, not field mapping templates".to_string(),
+            ));
+        }
+
+        if let Some(template_id) = template_id {
+            let template = self.get_template(template_id, connection.user_id).await?;
+            if template.erp_type != connection.erp_type.as_str() {
+                return Err(AppError::BadRequest(format!(
+                    "Template is for {}, the connection is {}",
+                    template.erp_type,
+                    connection.erp_type.as_str()
+                )));
+            }
+
+            let missing = template.field_mappings.missing_for_sync(connection.sync_lot_batch);
+            if !missing.is_empty() {
+                return Err(AppError::BadRequest(format!(
+                    "Template is missing fields this connection syncs: {}",
+                    missing.join(", ")
+                )));
+            }
+        }
+
+        let mut tx = self.db_pool.begin().await?;
+
+        sqlx::query("UPDATE erp_connections SET field_mapping_template_id = $2, updated_at = NOW() WHERE id = $1")
+            .bind(connection.id)
+            .bind(template_id)
+            .execute(&mut *tx)
+            .await?;
+
+        if let Some(template_id) = template_id {
+            sqlx::query("UPDATE erp_field_mapping_templates SET usage_count = COALESCE(usage_count, 0) + 1 WHERE id = $1")
+                .bind(template_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+
+        self.describe_connection(connection).await
+    }
+}
+
+fn validate_template_request(request: &SaveFieldMappingTemplateRequest) -> Result<()> {
+    if !["netsuite", "sap_s4hana"].contains(&request.erp_type.as_str()) {
+        return Err(AppError::BadRequest(
+            "Field mapping templates apply to 'netsuite' and 'sap_s4hana' connections".to_string(),
+        ));
+    }
+
+    let name = request.template_name.trim();
+    if name.is_empty() || name.len() > 100 {
+        return Err(AppError::BadRequest("template_name must be 1-100 characters".to_string()));
+    }
+
+    request.field_mappings.validate().map_err(AppError::BadRequest)
+}
+
+fn conflict_on_duplicate_name(e: sqlx::Error) -> AppError {
+    match e {
+        sqlx::Error::Database(ref db) if db.code().as_deref() == Some("23505") => AppError::Conflict,
+        e => AppError::Database(e),
+    }
+}
@@ -0,0 +1,465 @@
+// ERP Item Catalog Service
+// Caches the items of each connection's ERP locally (with the time they were fetched) so
+// the mapping UI can browse/search them and AI mapping discovery can reuse them instead of
+// fetching the ERP catalog live every time
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use sqlx::types::Json;
+use sqlx::PgPool;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::middleware::error_handling::{AppError, Result};
+use crate::services::erp::flat_file_format::parse_csv;
+use crate::services::erp::netsuite_client::{NetSuiteClient, NetSuiteError, NetSuiteSearchParams};
+use crate::services::erp::sap_client::{SapClient, SapError};
+use crate::services::erp::sftp_client::SftpClient;
+use crate::services::erp::{ErpConnection, ErpFieldMappingService, ErpType};
+
+/// Cached catalogs older than this are refetched before AI mapping discovery
+pub const CATALOG_MAX_AGE_HOURS: i64 = 24;
+
+// ============================================================================
+// Data Models
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct ErpCatalogItem {
+    pub erp_item_id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub quantity: f64,
+    /// ndc_code, lot_number, expiry_date, manufacturer, ... when the ERP provides them
+    #[schema(value_type = HashMap<String, String>)]
+    pub custom_fields: Json<HashMap<String, String>>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CatalogQuery {
+    /// Matches item ID, name or NDC code (case-insensitive)
+    pub query: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+    /// Refetch the catalog from the ERP before searching
+    pub refresh: Option<bool>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CatalogPage {
+    pub items: Vec<ErpCatalogItem>,
+    pub total: i64,
+    /// When the catalog was last fetched from the ERP (None = never)
+    pub fetched_at: Option<DateTime<Utc>>,
+    /// True when the catalog is older than CATALOG_MAX_AGE_HOURS
+    pub stale: bool,
+}
+
+/// An item as fetched from the ERP, before caching
+struct FetchedItem {
+    id: String,
+    name: String,
+    description: Option<String>,
+    quantity: f64,
+    custom_fields: HashMap<String, String>,
+}
+
+// ============================================================================
+// ERP Item Catalog Service
+// ============================================================================
+
+pub struct ErpItemCatalogService {
+    db_pool: PgPool,
+}
+
+impl ErpItemCatalogService {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self { db_pool }
+    }
+
+    /// The connection's full catalog, refetched from the ERP first when missing or stale
+    pub async fn items(&self, connection: &ErpConnection) -> Result<Vec<ErpCatalogItem>> {
+        if !self.is_fresh(self.fetched_at(connection.id).await?) {
+            self.refresh(connection).await?;
+        }
+
+        let items = sqlx::query_as::<_, ErpCatalogItem>(
+            r#"
+            SELECT erp_item_id, name, description, quantity, custom_fields, fetched_at
+            FROM erp_item_catalog
+            WHERE erp_connection_id = $1
+            ORDER BY name
+            "#
+        )
+        .bind(connection.id)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(items)
+    }
+
+    /// Search the cached catalog. The ERP is only called when the catalog has never been
+    /// fetched or a refresh is requested.
+    pub async fn search(&self, connection: &ErpConnection, params: &CatalogQuery) -> Result<CatalogPage> {
+        let mut fetched_at = self.fetched_at(connection.id).await?;
+        if fetched_at.is_none() || params.refresh.unwrap_or(false) {
+            fetched_at = Some(self.refresh(connection).await?);
+        }
+
+        let limit = params.limit.unwrap_or(50).clamp(1, 200);
+        let offset = params.offset.unwrap_or(0).max(0);
+        let pattern = params.query
+            .as_deref()
+            .map(str::trim)
+            .filter(|q| !q.is_empty())
+            .map(|q| format!("%{}%", q.to_lowercase()));
+
+        let items = sqlx::query_as::<_, ErpCatalogItem>(
+            r#"
+            SELECT erp_item_id, name, description, quantity, custom_fields, fetched_at
+            FROM erp_item_catalog
+            WHERE erp_connection_id = $1
+              AND ($2::text IS NULL
+                   OR lower(name) LIKE $2
+                   OR lower(erp_item_id) LIKE $2
+                   OR lower(custom_fields->>'ndc_code') LIKE $2)
+            ORDER BY name
+            LIMIT $3 OFFSET $4
+            "#
+        )
+        .bind(connection.id)
+        .bind(&pattern)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let total = sqlx::query_scalar::<_, i64>(
+            r#"
+            SELECT COUNT(*)
+            FROM erp_item_catalog
+            WHERE erp_connection_id = $1
+              AND ($2::text IS NULL
+                   OR lower(name) LIKE $2
+                   OR lower(erp_item_id) LIKE $2
+                   OR lower(custom_fields->>'ndc_code') LIKE $2)
+            "#
+        )
+        .bind(connection.id)
+        .bind(&pattern)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        Ok(CatalogPage {
+            items,
+            total,
+            fetched_at,
+            stale: !self.is_fresh(fetched_at),
+        })
+    }
+
+    /// Fetch the catalog from the ERP and replace the cached copy. Returns the fetch time.
+    pub async fn refresh(&self, connection: &ErpConnection) -> Result<DateTime<Utc>> {
+        tracing::info!("Fetching item catalog from {} ERP", connection.erp_type.as_str());
+
+        let items = match connection.erp_type {
+            ErpType::NetSuite => self.fetch_netsuite_inventory(connection).await?,
+            ErpType::SapS4Hana => self.fetch_sap_inventory(connection).await?,
+            ErpType::FlatFile => self.fetch_flat_file_inventory(connection).await?,
+        };
+
+        let fetched_at = Utc::now();
+        let mut tx = self.db_pool.begin().await?;
+
+        sqlx::query("DELETE FROM erp_item_catalog WHERE erp_connection_id = $1")
+            .bind(connection.id)
+            .execute(&mut *tx)
+            .await?;
+
+        for item in &items {
+            sqlx::query(
+                r#"
+                INSERT INTO erp_item_catalog (
+                    erp_connection_id, erp_item_id, name, description, quantity, custom_fields, fetched_at
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7)
+                ON CONFLICT (erp_connection_id, erp_item_id) DO UPDATE
+                SET name = EXCLUDED.name,
+                    description = EXCLUDED.description,
+                    quantity = EXCLUDED.quantity,
+                    custom_fields = EXCLUDED.custom_fields
+                "#
+            )
+            .bind(connection.id)
+            .bind(&item.id)
+            .bind(&item.name)
+            .bind(&item.description)
+            .bind(item.quantity)
+            .bind(Json(&item.custom_fields))
+            .bind(fetched_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        tracing::info!("Cached {} ERP items for connection {}", items.len(), connection.id);
+
+        Ok(fetched_at)
+    }
+
+    async fn fetched_at(&self, connection_id: Uuid) -> Result<Option<DateTime<Utc>>> {
+        let fetched_at = sqlx::query_scalar::<_, Option<DateTime<Utc>>>(
+            "SELECT MAX(fetched_at) FROM erp_item_catalog WHERE erp_connection_id = $1"
+        )
+        .bind(connection_id)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        Ok(fetched_at)
+    }
+
+    fn is_fresh(&self, fetched_at: Option<DateTime<Utc>>) -> bool {
+        fetched_at.is_some_and(|at| Utc::now() - at < Duration::hours(CATALOG_MAX_AGE_HOURS))
+    }
+
+    // ========================================================================
+    // ERP Fetching
+    // ========================================================================
+
+    /// Read the newest stock file waiting in the flat-file connection's inbound directory
+    async fn fetch_flat_file_inventory(&self, connection: &ErpConnection) -> Result<Vec<FetchedItem>> {
+        let sftp_config = connection.flat_file_config.as_ref()
+            .ok_or_else(|| AppError::Internal(anyhow::anyhow!("SFTP credentials not found")))?;
+        let mapping = connection.column_mapping.clone().unwrap_or_default();
+
+        let client = SftpClient::new(sftp_config.clone())
+            .map_err(|e| AppError::BadRequest(format!("SFTP configuration error: {}", e)))?;
+
+        let files = client.list_inbound().await
+            .map_err(|e| AppError::Internal(anyhow::anyhow!("SFTP error: {}", e)))?;
+        let Some(newest) = files.last() else {
+            return Err(AppError::NotFound(
+                "No stock files in the inbound directory to discover mappings from".to_string()
+            ));
+        };
+
+        let contents = client.download(&newest.path).await
+            .map_err(|e| AppError::Internal(anyhow::anyhow!("SFTP error: {}", e)))?;
+        let parsed = parse_csv(&contents, &mapping)
+            .map_err(|e| AppError::BadRequest(format!("Could not parse {}: {}", newest.name, e)))?;
+
+        tracing::info!("Flat file {} has {} stock rows", newest.name, parsed.rows.len());
+
+        Ok(parsed.rows.into_iter().map(|row| {
+            let mut custom_fields = HashMap::new();
+            if let Some(ndc) = row.ndc_code {
+                custom_fields.insert("ndc_code".to_string(), ndc);
+            }
+            if let Some(lot) = row.lot_number {
+                custom_fields.insert("lot_number".to_string(), lot);
+            }
+            if let Some(expiry) = row.expiry_date {
+                custom_fields.insert("expiry_date".to_string(), expiry.to_string());
+            }
+            if let Some(location) = row.location_id {
+                custom_fields.insert("location_id".to_string(), location);
+            }
+
+            FetchedItem {
+                name: row.item_name.unwrap_or_else(|| row.item_id.clone()),
+                id: row.item_id,
+                description: None,
+                quantity: row.quantity as f64,
+                custom_fields,
+            }
+        }).collect())
+    }
+
+    /// Fetch inventory from NetSuite via SuiteTalk REST API
+    async fn fetch_netsuite_inventory(&self, connection: &ErpConnection) -> Result<Vec<FetchedItem>> {
+        let netsuite_config = connection.netsuite_config.as_ref()
+            .ok_or_else(|| AppError::Internal(anyhow::anyhow!("NetSuite credentials not found")))?;
+
+        // Create NetSuite client
+        let client = NetSuiteClient::new(netsuite_config.clone())
+            .map_err(|e| self.map_netsuite_error(e))?;
+
+        // Custom fields come from the connection's field mapping template
+        let field_mapping = ErpFieldMappingService::new(self.db_pool.clone())
+            .mapping_for(connection.id, &connection.erp_type)
+            .await?;
+
+        let mut fields: Vec<String> = ["id", "itemId", "displayName", "quantityOnHand", "description", "manufacturer"]
+            .iter()
+            .map(|f| f.to_string())
+            .collect();
+        fields.extend(
+            [&field_mapping.ndc_code, &field_mapping.lot_number, &field_mapping.expiry_date]
+                .into_iter()
+                .flatten()
+                .cloned(),
+        );
+
+        // Search for inventory items (limit to 1000 for performance)
+        let search_params = NetSuiteSearchParams {
+            q: None, // Get all inventory items
+            limit: Some(1000),
+            offset: Some(0),
+            fields: Some(fields),
+        };
+
+        tracing::info!("Calling NetSuite inventory search API...");
+        let search_result = client.search_inventory(search_params).await
+            .map_err(|e| self.map_netsuite_error(e))?;
+
+        tracing::info!("NetSuite returned {} inventory items", search_result.items.len());
+
+        // Transform NetSuite items to generic ERP inventory items
+        let erp_items = search_result.items.into_iter().map(|ns_item| {
+            let mut custom_fields = HashMap::new();
+
+            // Add NDC code if present
+            if let Some(ndc) = ns_item.custom_field(field_mapping.ndc_code.as_deref()) {
+                custom_fields.insert("ndc_code".to_string(), ndc);
+            }
+
+            // Add lot number if present
+            if let Some(lot) = ns_item.custom_field(field_mapping.lot_number.as_deref()) {
+                custom_fields.insert("lot_number".to_string(), lot);
+            }
+
+            // Add expiry date if present
+            if let Some(expiry) = ns_item.custom_field(field_mapping.expiry_date.as_deref()) {
+                custom_fields.insert("expiry_date".to_string(), expiry);
+            }
+
+            // Add manufacturer if present
+            if let Some(ref mfg) = ns_item.manufacturer {
+                custom_fields.insert("manufacturer".to_string(), mfg.name.clone());
+            }
+
+            FetchedItem {
+                id: ns_item.id,
+                name: ns_item.display_name,
+                description: ns_item.description,
+                quantity: ns_item.quantity_on_hand.unwrap_or(0.0),
+                custom_fields,
+            }
+        }).collect();
+
+        Ok(erp_items)
+    }
+
+    /// Fetch inventory from SAP via OData API
+    async fn fetch_sap_inventory(&self, connection: &ErpConnection) -> Result<Vec<FetchedItem>> {
+        let sap_config = connection.sap_config.as_ref()
+            .ok_or_else(|| AppError::Internal(anyhow::anyhow!("SAP credentials not found")))?;
+
+        // Create SAP client
+        let client = SapClient::new(sap_config.clone())
+            .map_err(|e| self.map_sap_error(e))?;
+
+        tracing::info!("Fetching SAP product master data...");
+
+        // For SAP, we need to search products and then get their stock
+        // This is a simplified approach - in production you might want pagination
+        let products = client.search_products("").await // Empty search gets all products (limited by SAP)
+            .map_err(|e| self.map_sap_error(e))?;
+
+        tracing::info!("SAP returned {} products, fetching stock data...", products.len());
+
+        let mut erp_items = Vec::new();
+
+        // For each product, get stock information
+        for product in products.into_iter().take(1000) { // Limit to 1000 for performance
+            // Get stock for all locations
+            let stock_result = client.get_material_stock_all_locations(&product.product).await;
+
+            match stock_result {
+                Ok(stock_locations) => {
+                    // Calculate total quantity across all locations
+                    let total_quantity: f64 = stock_locations.iter()
+                        .filter_map(|loc| loc.stock_quantity.parse::<f64>().ok())
+                        .sum();
+
+                    let mut custom_fields = HashMap::new();
+
+                    // Add manufacturer if present
+                    if let Some(mfg) = product.manufacturer {
+                        custom_fields.insert("manufacturer".to_string(), mfg);
+                    }
+
+                    // Add product group if present
+                    if let Some(group) = product.product_group {
+                        custom_fields.insert("product_group".to_string(), group);
+                    }
+
+                    // Add base unit
+                    custom_fields.insert("base_unit".to_string(), product.base_unit.clone());
+
+                    erp_items.push(FetchedItem {
+                        id: product.product.clone(),
+                        name: product.product, // SAP uses material number as name
+                        description: product.description,
+                        quantity: total_quantity,
+                        custom_fields,
+                    });
+                }
+                Err(e) => {
+                    // Log error but continue processing other products
+                    tracing::warn!("Failed to get stock for product {}: {:?}", product.product, e);
+                    // Still add the product with zero quantity
+                    erp_items.push(FetchedItem {
+                        id: product.product.clone(),
+                        name: product.product,
+                        description: product.description,
+                        quantity: 0.0,
+                        custom_fields: HashMap::new(),
+                    });
+                }
+            }
+        }
+
+        tracing::info!("SAP inventory fetch complete: {} items with stock data", erp_items.len());
+
+        Ok(erp_items)
+    }
+
+    /// Map NetSuite errors to AppError
+    fn map_netsuite_error(&self, error: NetSuiteError) -> AppError {
+        match error {
+            NetSuiteError::AuthError(msg) => {
+                tracing::error!("NetSuite authentication failed: {}", msg);
+                AppError::Unauthorized
+            },
+            NetSuiteError::RateLimitExceeded => AppError::TooManyRequests("NetSuite API rate limit exceeded. Please try again later.".to_string()),
+            NetSuiteError::CircuitOpen(retry_after) => AppError::TooManyRequests(format!("NetSuite calls are paused after repeated failures. Retry in {}s.", retry_after)),
+            NetSuiteError::NotFound(msg) => AppError::NotFound(format!("NetSuite resource not found: {}", msg)),
+            NetSuiteError::ApiError(status, msg) => AppError::Internal(anyhow::anyhow!("NetSuite API error ({}): {}", status, msg)),
+            NetSuiteError::NetworkError(e) => AppError::Internal(anyhow::anyhow!("NetSuite network error: {}", e)),
+            NetSuiteError::ConfigError(msg) => AppError::BadRequest(format!("NetSuite configuration error: {}", msg)),
+            _ => AppError::Internal(anyhow::anyhow!("NetSuite error: {:?}", error)),
+        }
+    }
+
+    /// Map SAP errors to AppError
+    fn map_sap_error(&self, error: SapError) -> AppError {
+        match error {
+            SapError::AuthError(msg) => {
+                tracing::error!("SAP authentication failed: {}", msg);
+                AppError::Unauthorized
+            },
+            SapError::RateLimitExceeded => AppError::TooManyRequests("SAP API rate limit exceeded. Please try again later.".to_string()),
+            SapError::CircuitOpen(retry_after) => AppError::TooManyRequests(format!("SAP calls are paused after repeated failures. Retry in {}s.", retry_after)),
+            SapError::NotFound(msg) => AppError::NotFound(format!("SAP resource not found: {}", msg)),
+            SapError::ApiError(status, msg) => AppError::Internal(anyhow::anyhow!("SAP API error ({}): {}", status, msg)),
+            SapError::NetworkError(e) => AppError::Internal(anyhow::anyhow!("SAP network error: {}", e)),
+            SapError::ConfigError(msg) => AppError::BadRequest(format!("SAP configuration error: {}", msg)),
+            SapError::ODataError(msg) => AppError::Internal(anyhow::anyhow!("SAP OData error: {}", msg)),
+            _ => AppError::Internal(anyhow::anyhow!("SAP error: {:?}", error)),
+        }
+    }
+}
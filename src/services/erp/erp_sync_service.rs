@@ -5,14 +5,28 @@
 use sqlx::PgPool;
 use uuid::Uuid;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use chrono::{DateTime, Utc};
 use thiserror::Error;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
 
 use crate::services::erp::{
-    ErpConnectionService, ErpConnection, ErpType,
-    NetSuiteClient, SapClient,
+    ErpConnectionService, ErpConnection, ErpFieldMapping, ErpFieldMappingService, ErpType,
+    NetSuiteClient, SapClient, SapError, SftpClient,
 };
+use crate::services::erp::api_guard::{self, CircuitState};
+use crate::services::erp::bulk_sync::{self, PlannedOutcome, QuantityChange};
+use crate::services::erp::erp_connection_service::SyncDirection as ConnectionSyncDirection;
+use crate::services::erp::flat_file_format::{checksum_sha256, parse_csv, write_csv, FlatFileRow};
+use crate::services::erp::netsuite_client::{InventoryPages, NetSuiteInventoryItem};
+use crate::services::erp::sap_client::{
+    MaterialStock, MaterialStockPages, PurchaseOrder, PurchaseOrderItem, PurchaseOrderItems, SalesOrder,
+    SalesOrderItem, SalesOrderItems,
+};
+use crate::services::erp::sftp_client::{RemoteFile, MAX_FILE_BYTES};
+use crate::services::erp::webhook_events::{ErpItemUpdate, WebhookEvent};
+use crate::services::{InFlightGuard, RuntimeSettings, Shutdown, SyncLogTable};
 use crate::repositories::inventory_repo::InventoryRepository;
 use crate::models::inventory::Inventory;
 
@@ -40,12 +54,23 @@ pub enum SyncError {
     #[error("SAP error: {0}")]
     SapError(String),
 
+    #[error("SFTP error: {0}")]
+    SftpError(String),
+
     #[error("Mapping not found for inventory: {0}")]
     MappingNotFound(Uuid),
 }
 
 pub type Result<T> = std::result::Result<T, SyncError>;
 
+/// Delta cursors older than this are discarded and a full sync runs instead, which also
+/// catches any drift the change feeds missed
+const DELTA_TOKEN_MAX_AGE_DAYS: i64 = 7;
+
+/// NetSuite lastModifiedDate filters have minute precision; each delta search reaches
+/// back this far before the cursor
+const DELTA_OVERLAP_MINUTES: i64 = 5;
+
 // ============================================================================
 // Data Models
 // ============================================================================
@@ -58,7 +83,7 @@ pub enum SyncDirection {
     Bidirectional,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct SyncResult {
     pub items_synced: i32,
     pub items_failed: i32,
@@ -69,6 +94,20 @@ pub struct SyncResult {
     pub errors: Vec<SyncItemError>,
 }
 
+impl SyncResult {
+    pub fn combine(self, other: SyncResult) -> SyncResult {
+        SyncResult {
+            items_synced: self.items_synced + other.items_synced,
+            items_failed: self.items_failed + other.items_failed,
+            items_skipped: self.items_skipped + other.items_skipped,
+            items_created: self.items_created + other.items_created,
+            items_updated: self.items_updated + other.items_updated,
+            conflicts_detected: self.conflicts_detected + other.conflicts_detected,
+            errors: [self.errors, other.errors].concat(),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Clone)]
 pub struct SyncItemError {
     pub item_id: String,
@@ -83,9 +122,76 @@ pub struct InventoryMapping {
     pub atlas_inventory_id: Uuid,
     pub erp_item_id: String,
     pub erp_location_id: Option<String>,
+    /// Subsidiary or plant the mapping syncs against (None: the connection default)
+    pub erp_entity_id: Option<String>,
     pub sync_enabled: bool,
 }
 
+/// A sales or purchase order created in an ERP for a marketplace transaction. The row ID
+/// is the idempotency key sent to the ERP.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct OrderExport {
+    pub id: Uuid,
+    pub transaction_id: Uuid,
+    pub erp_connection_id: Uuid,
+    pub order_type: String,
+    pub status: String,
+    pub erp_document_id: Option<String>,
+    pub erp_document_number: Option<String>,
+    pub attempts: i32,
+    pub error_message: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Which side of a marketplace transaction an ERP order is booked for
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum OrderSide {
+    /// Seller's ERP: sales order against the configured marketplace customer
+    Sales,
+    /// Buyer's ERP: purchase order against the configured marketplace vendor
+    Purchase,
+}
+
+impl OrderSide {
+    fn order_type(&self) -> &'static str {
+        match self {
+            OrderSide::Sales => "sales_order",
+            OrderSide::Purchase => "purchase_order",
+        }
+    }
+}
+
+/// The completed transaction an ERP order is built from
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct OrderSource {
+    transaction_id: Uuid,
+    seller_id: Uuid,
+    buyer_id: Uuid,
+    quantity: i32,
+    unit_price: rust_decimal::Decimal,
+    status: Option<String>,
+    transaction_date: Option<DateTime<Utc>>,
+    inventory_id: Uuid,
+    pharmaceutical_id: Uuid,
+}
+
+/// Result of sending an order to the ERP
+enum OrderOutcome {
+    Created { document_id: String, document_number: String },
+    Sandbox,
+}
+
+/// What applying an ERP quantity did to the mapped Atlas inventory
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum QuantityOutcome {
+    Updated,
+    /// Atlas wins: the ERP value was ignored
+    Kept,
+    /// Manual resolution: queued in erp_conflict_queue
+    Conflict,
+}
+
 // ============================================================================
 // ERP Sync Service
 // ============================================================================
@@ -93,6 +199,7 @@ pub struct InventoryMapping {
 pub struct ErpSyncService {
     db_pool: PgPool,
     connection_service: ErpConnectionService,
+    field_mapping_service: ErpFieldMappingService,
     inventory_repo: InventoryRepository,
 }
 
@@ -100,6 +207,7 @@ impl ErpSyncService {
     pub fn new(db_pool: PgPool) -> Self {
         Self {
             connection_service: ErpConnectionService::new(db_pool.clone()),
+            field_mapping_service: ErpFieldMappingService::new(db_pool.clone()),
             inventory_repo: InventoryRepository::new(db_pool.clone()),
             db_pool,
         }
@@ -131,25 +239,34 @@ impl ErpSyncService {
         }
 
         // 4. Sync to appropriate ERP
+        let fields = self.field_mapping_for(&connection).await?;
         match connection.erp_type {
-            ErpType::NetSuite => self.sync_to_netsuite(&connection, &inventory, &mapping).await,
-            ErpType::SapS4Hana => self.sync_to_sap(&connection, &inventory, &mapping).await,
+            ErpType::NetSuite => self.sync_to_netsuite(&connection, &inventory, &mapping, &fields, None).await,
+            ErpType::SapS4Hana => self.sync_to_sap(&connection, &inventory, &mapping, &fields, None).await,
+            ErpType::FlatFile => {
+                // Flat files carry the whole stock list; the item goes out with the next export
+                tracing::debug!("Inventory {} will be included in the next flat-file export", inventory_id);
+                Ok(())
+            }
         }
     }
 
     /// Sync from ERP to Atlas (pull updates)
-    pub async fn sync_from_erp_to_atlas(&self, connection_id: Uuid) -> Result<SyncResult> {
+    pub async fn sync_from_erp_to_atlas(&self, connection_id: Uuid, triggered_by: &str) -> Result<SyncResult> {
         let connection = self.connection_service
             .get_connection_by_id(connection_id)
             .await
             .map_err(|e| SyncError::ConnectionError(e.to_string()))?;
 
-        let sync_log_id = self.create_sync_log(&connection, "erp_to_atlas", "manual").await?;
+        self.field_mapping_for(&connection).await?;
+
+        let (sync_log_id, _in_flight) = self.create_sync_log(&connection, "erp_to_atlas", triggered_by).await?;
         let start_time = Utc::now();
 
         let result = match connection.erp_type {
-            ErpType::NetSuite => self.sync_from_netsuite(&connection).await,
-            ErpType::SapS4Hana => self.sync_from_sap(&connection).await,
+            ErpType::NetSuite => self.sync_from_netsuite(&connection, sync_log_id).await,
+            ErpType::SapS4Hana => self.sync_from_sap(&connection, sync_log_id).await,
+            ErpType::FlatFile => self.sync_from_flat_file(&connection, sync_log_id).await,
         };
 
         let duration = (Utc::now() - start_time).num_seconds() as i32;
@@ -159,33 +276,31 @@ impl ErpSyncService {
     }
 
     /// Bidirectional sync (both directions)
-    pub async fn sync_bidirectional(&self, connection_id: Uuid) -> Result<SyncResult> {
+    pub async fn sync_bidirectional(&self, connection_id: Uuid, triggered_by: &str) -> Result<SyncResult> {
         // First sync Atlas → ERP
-        let atlas_to_erp = self.sync_atlas_to_erp(connection_id).await?;
+        let atlas_to_erp = self.sync_atlas_to_erp(connection_id, triggered_by).await?;
 
         // Then sync ERP → Atlas
-        let erp_to_atlas = self.sync_from_erp_to_atlas(connection_id).await?;
+        let erp_to_atlas = self.sync_from_erp_to_atlas(connection_id, triggered_by).await?;
 
         // Combine results
-        Ok(SyncResult {
-            items_synced: atlas_to_erp.items_synced + erp_to_atlas.items_synced,
-            items_failed: atlas_to_erp.items_failed + erp_to_atlas.items_failed,
-            items_skipped: atlas_to_erp.items_skipped + erp_to_atlas.items_skipped,
-            items_created: atlas_to_erp.items_created + erp_to_atlas.items_created,
-            items_updated: atlas_to_erp.items_updated + erp_to_atlas.items_updated,
-            conflicts_detected: atlas_to_erp.conflicts_detected + erp_to_atlas.conflicts_detected,
-            errors: [atlas_to_erp.errors, erp_to_atlas.errors].concat(),
-        })
+        Ok(atlas_to_erp.combine(erp_to_atlas))
     }
 
     /// Sync all Atlas inventory to ERP
-    pub async fn sync_atlas_to_erp(&self, connection_id: Uuid) -> Result<SyncResult> {
+    pub async fn sync_atlas_to_erp(&self, connection_id: Uuid, triggered_by: &str) -> Result<SyncResult> {
         let connection = self.connection_service
             .get_connection_by_id(connection_id)
             .await
             .map_err(|e| SyncError::ConnectionError(e.to_string()))?;
 
-        let sync_log_id = self.create_sync_log(&connection, "atlas_to_erp", "manual").await?;
+        if connection.erp_type == ErpType::FlatFile {
+            return self.export_flat_file(&connection, triggered_by).await;
+        }
+
+        let fields = self.field_mapping_for(&connection).await?;
+
+        let (sync_log_id, _in_flight) = self.create_sync_log(&connection, "atlas_to_erp", triggered_by).await?;
         let start_time = Utc::now();
 
         // Get all inventory for user
@@ -203,7 +318,7 @@ impl ErpSyncService {
         };
 
         for inventory in inventory_items {
-            match self.sync_single_item_to_erp(&connection, &inventory).await {
+            match self.sync_single_item_to_erp(&connection, &inventory, &fields, sync_log_id).await {
                 Ok(_) => {
                     result.items_synced += 1;
                     result.items_updated += 1;
@@ -226,6 +341,88 @@ impl ErpSyncService {
         Ok(result)
     }
 
+    /// Sync in the given direction
+    pub async fn sync_in_direction(
+        &self,
+        connection_id: Uuid,
+        direction: SyncDirection,
+        triggered_by: &str,
+    ) -> Result<SyncResult> {
+        match direction {
+            SyncDirection::AtlasToErp => self.sync_atlas_to_erp(connection_id, triggered_by).await,
+            SyncDirection::ErpToAtlas => self.sync_from_erp_to_atlas(connection_id, triggered_by).await,
+            SyncDirection::Bidirectional => self.sync_bidirectional(connection_id, triggered_by).await,
+        }
+    }
+
+    /// The connection's field mapping; syncs do not run while it is incomplete
+    async fn field_mapping_for(&self, connection: &ErpConnection) -> Result<ErpFieldMapping> {
+        self.field_mapping_service
+            .require_complete_mapping(connection)
+            .await
+            .map_err(|e| SyncError::SyncFailed(e.to_string()))
+    }
+
+    /// Scheduled run of a connection in its default direction. Returns `None` when
+    /// the connection is already syncing.
+    pub async fn run_scheduled_sync(&self, connection_id: Uuid) -> Result<Option<SyncResult>> {
+        let connection = self.connection_service
+            .get_connection_by_id(connection_id)
+            .await
+            .map_err(|e| SyncError::ConnectionError(e.to_string()))?;
+
+        // Syncs pause while the ERP circuit is open; the next due run after the cool-down probes it
+        let circuit = api_guard::circuit_status(connection_id);
+        if circuit.state == CircuitState::Open {
+            tracing::info!(
+                "Skipping scheduled sync for connection {}: ERP circuit open, retry in {}s",
+                connection_id, circuit.retry_after_seconds.unwrap_or(0)
+            );
+            return Ok(None);
+        }
+
+        let claimed = self.connection_service
+            .try_start_sync(connection_id)
+            .await
+            .map_err(|e| SyncError::ConnectionError(e.to_string()))?;
+        if !claimed {
+            return Ok(None);
+        }
+
+        let direction = match connection.default_sync_direction {
+            ConnectionSyncDirection::AtlasToErp => SyncDirection::AtlasToErp,
+            ConnectionSyncDirection::ErpToAtlas => SyncDirection::ErpToAtlas,
+            ConnectionSyncDirection::Bidirectional => SyncDirection::Bidirectional,
+        };
+
+        let start_time = Utc::now();
+        let result = self.sync_in_direction(connection_id, direction, "scheduler").await;
+        self.finish_connection_sync(connection_id, &result, start_time).await?;
+
+        result.map(Some)
+    }
+
+    /// Release a connection claimed with `ErpConnectionService::try_start_sync`
+    pub async fn finish_connection_sync(
+        &self,
+        connection_id: Uuid,
+        result: &Result<SyncResult>,
+        start_time: DateTime<Utc>,
+    ) -> Result<()> {
+        let (status, error) = match result {
+            Ok(r) if r.items_failed > 0 => ("partial", None),
+            Ok(_) => ("success", None),
+            Err(e) if Shutdown::requested() => ("interrupted", Some(e.to_string())),
+            Err(e) => ("failed", Some(e.to_string())),
+        };
+        let duration = (Utc::now() - start_time).num_seconds() as i32;
+
+        self.connection_service
+            .finish_sync(connection_id, status, duration, error.as_deref())
+            .await
+            .map_err(|e| SyncError::ConnectionError(e.to_string()))
+    }
+
     // ========================================================================
     // NetSuite Sync Implementation
     // ========================================================================
@@ -235,6 +432,8 @@ impl ErpSyncService {
         connection: &ErpConnection,
         inventory: &Inventory,
         mapping: &InventoryMapping,
+        fields: &ErpFieldMapping,
+        sync_log_id: Option<Uuid>,
     ) -> Result<()> {
         let config = connection.netsuite_config.as_ref()
             .ok_or_else(|| SyncError::SyncFailed("NetSuite config not available".to_string()))?;
@@ -242,8 +441,20 @@ impl ErpSyncService {
         let client = NetSuiteClient::new(config.clone())
             .map_err(|e| SyncError::NetSuiteError(e.to_string()))?;
 
+        // Without an explicit location, the subsidiary's default location
+        let location_id = mapping.erp_location_id.as_deref()
+            .or_else(|| {
+                mapping.erp_entity_id.as_deref()
+                    .and_then(|id| connection.entity(id))
+                    .and_then(|entity| entity.default_location_id.as_deref())
+            })
+            .unwrap_or("1");
+
+        if connection.sandbox_mode {
+            return self.capture_netsuite_writes(&client, connection, inventory, mapping, fields, location_id, sync_log_id).await;
+        }
+
         // Update quantity
-        let location_id = mapping.erp_location_id.as_deref().unwrap_or("1");
         client.update_inventory_quantity(
             &mapping.erp_item_id,
             location_id,
@@ -254,10 +465,8 @@ impl ErpSyncService {
 
         // Update custom fields if enabled
         if connection.sync_lot_batch {
-            let mut custom_fields = HashMap::new();
-            // Use batch_number as lot_number
-            custom_fields.insert("custitem_lot_number".to_string(), inventory.batch_number.clone());
-            custom_fields.insert("custitem_expiry_date".to_string(), inventory.expiry_date.to_string());
+            // Use batch_number as lot_number, in the fields named by the mapping template
+            let custom_fields = fields.lot_fields(&inventory.batch_number, &inventory.expiry_date.to_string());
 
             // Note: To add NDC code, we would need to fetch the pharmaceutical details separately
             // For now, we'll skip it as the Inventory model doesn't include nested pharmaceutical data
@@ -273,117 +482,140 @@ impl ErpSyncService {
         Ok(())
     }
 
-    async fn sync_from_netsuite(&self, connection: &ErpConnection) -> Result<SyncResult> {
+    /// Items modified since the connection's cursor (a lastModifiedDate saved search), or
+    /// every mapped item when there is no usable cursor. Search pages are applied as they
+    /// arrive. The cursor advances to the start of this sync only if every item applied,
+    /// so failed items are fetched again.
+    async fn sync_from_netsuite(&self, connection: &ErpConnection, sync_log_id: Uuid) -> Result<SyncResult> {
+        let started_at = Utc::now();
+
+        let since = self.usable_delta_token(connection)
+            .and_then(|token| DateTime::parse_from_rfc3339(token).ok())
+            .map(|since| since.with_timezone(&Utc) - chrono::Duration::minutes(DELTA_OVERLAP_MINUTES));
+
+        let result = match since {
+            Some(since) => {
+                let client = self.netsuite_client(connection)?;
+                let mut pages = client.inventory_modified_since(since);
+
+                match pages.next_page().await {
+                    Ok(first) => {
+                        self.mark_sync_log_incremental(sync_log_id).await?;
+                        self.apply_netsuite_pages(connection, first.unwrap_or_default(), &mut pages).await?
+                    }
+                    Err(e) => {
+                        tracing::warn!(
+                            "NetSuite delta search failed for connection {}, running full sync: {}",
+                            connection.id, e
+                        );
+                        self.sync_from_netsuite_full(connection).await?
+                    }
+                }
+            }
+            None => self.sync_from_netsuite_full(connection).await?,
+        };
+
+        if result.items_failed == 0 {
+            self.save_delta_token(connection.id, &started_at.to_rfc3339()).await?;
+        }
+
+        Ok(result)
+    }
+
+    fn netsuite_client(&self, connection: &ErpConnection) -> Result<NetSuiteClient> {
         let config = connection.netsuite_config.as_ref()
             .ok_or_else(|| SyncError::SyncFailed("NetSuite config not available".to_string()))?;
 
-        let client = NetSuiteClient::new(config.clone())
-            .map_err(|e| SyncError::NetSuiteError(e.to_string()))?;
+        NetSuiteClient::new(config.clone())
+            .map_err(|e| SyncError::NetSuiteError(e.to_string()))
+    }
 
-        // Get all mappings for this connection
+    /// Apply changed NetSuite items page by page; unmapped items are ignored
+    async fn apply_netsuite_pages(
+        &self,
+        connection: &ErpConnection,
+        first: Vec<NetSuiteInventoryItem>,
+        pages: &mut InventoryPages<'_>,
+    ) -> Result<SyncResult> {
         let mappings = self.get_mappings_for_connection(connection.id).await?;
+        let mut by_item: HashMap<&str, Vec<&InventoryMapping>> = HashMap::new();
+        for mapping in &mappings {
+            by_item.entry(mapping.erp_item_id.as_str()).or_default().push(mapping);
+        }
 
-        let mut result = SyncResult {
-            items_synced: 0,
-            items_failed: 0,
-            items_skipped: 0,
-            items_created: 0,
-            items_updated: 0,
-            conflicts_detected: 0,
-            errors: Vec::new(),
-        };
+        let mut result = SyncResult::default();
+        let mut page = Some(first);
 
-        for mapping in mappings {
-            if !mapping.sync_enabled {
-                result.items_skipped += 1;
-                continue;
-            }
+        while let Some(items) = page {
+            let changes = items
+                .iter()
+                .flat_map(|item| {
+                    let quantity = netsuite_quantity(item);
+                    by_item.get(item.id.as_str())
+                        .into_iter()
+                        .flatten()
+                        .map(move |mapping| (*mapping, quantity))
+                })
+                .collect::<Vec<_>>();
 
-            match client.get_inventory_item(&mapping.erp_item_id).await {
-                Ok(netsuite_item) => {
-                    // Update Atlas inventory with NetSuite data
-                    match self.update_atlas_from_netsuite(&mapping, &netsuite_item, connection).await {
-                        Ok(_) => {
-                            result.items_synced += 1;
-                            result.items_updated += 1;
-                        }
-                        Err(e) => {
-                            result.items_failed += 1;
-                            result.errors.push(SyncItemError {
-                                item_id: mapping.erp_item_id.clone(),
-                                error_message: e.to_string(),
-                                error_type: "update_failed".to_string(),
-                            });
-                        }
-                    }
-                }
-                Err(e) => {
-                    result.items_failed += 1;
-                    result.errors.push(SyncItemError {
-                        item_id: mapping.erp_item_id.clone(),
-                        error_message: e.to_string(),
-                        error_type: "fetch_failed".to_string(),
-                    });
-                }
-            }
+            result = result.combine(self.apply_erp_quantities(connection, &changes).await?);
+
+            page = pages.next_page().await
+                .map_err(|e| SyncError::NetSuiteError(e.to_string()))?;
         }
 
         Ok(result)
     }
 
-    async fn update_atlas_from_netsuite(
-        &self,
-        mapping: &InventoryMapping,
-        netsuite_item: &crate::services::erp::netsuite_client::NetSuiteInventoryItem,
-        connection: &ErpConnection,
-    ) -> Result<()> {
-        // Get current Atlas inventory
-        let mut inventory = self.inventory_repo.find_by_id(mapping.atlas_inventory_id).await
-            .map_err(|e| SyncError::SyncFailed(format!("Failed to get inventory: {}", e)))?
-            .ok_or_else(|| SyncError::SyncFailed(format!("Inventory {} not found", mapping.atlas_inventory_id)))?;
-
-        // Get quantity from NetSuite (handle locations)
-        let netsuite_quantity = if let Some(ref locations) = netsuite_item.locations {
-            if let Some(location) = locations.items.first() {
-                location.quantity_on_hand.unwrap_or(0.0) as i32
-            } else {
-                netsuite_item.quantity_on_hand.unwrap_or(0.0) as i32
-            }
-        } else {
-            netsuite_item.quantity_on_hand.unwrap_or(0.0) as i32
-        };
+    /// Fetch every mapped item, a batch of internal IDs per search
+    async fn sync_from_netsuite_full(&self, connection: &ErpConnection) -> Result<SyncResult> {
+        let client = self.netsuite_client(connection)?;
+        let mappings = self.get_mappings_for_connection(connection.id).await?;
 
-        // Check for conflicts
-        if inventory.quantity != netsuite_quantity {
-            // Apply conflict resolution
-            match connection.conflict_resolution {
-                crate::services::erp::erp_connection_service::ConflictResolution::ErpWins => {
-                    inventory.quantity = netsuite_quantity;
-                }
-                crate::services::erp::erp_connection_service::ConflictResolution::AtlasWins => {
-                    // Keep Atlas value, skip update
-                    return Ok(());
-                }
-                crate::services::erp::erp_connection_service::ConflictResolution::Manual => {
-                    // Log conflict for manual resolution
-                    self.create_conflict_record(mapping, "quantity_mismatch").await?;
-                    return Ok(());
-                }
-                crate::services::erp::erp_connection_service::ConflictResolution::LatestTimestamp => {
-                    // Use NetSuite value (assume it's latest)
-                    inventory.quantity = netsuite_quantity;
+        let mut result = SyncResult::default();
+
+        for batch in mappings.chunks(bulk_sync::NETSUITE_ITEM_BATCH_SIZE) {
+            let item_ids = batch.iter()
+                .map(|m| m.erp_item_id.clone())
+                .collect::<HashSet<_>>()
+                .into_iter()
+                .collect::<Vec<_>>();
+
+            let mut quantities: HashMap<String, i32> = HashMap::new();
+            let mut pages = client.inventory_items_by_id(&item_ids);
+            let fetched = loop {
+                match pages.next_page().await {
+                    Ok(Some(items)) => {
+                        quantities.extend(items.iter().map(|item| (item.id.clone(), netsuite_quantity(item))));
+                    }
+                    Ok(None) => break Ok(()),
+                    Err(e) => break Err(e),
                 }
+            };
+
+            let mut changes = Vec::with_capacity(batch.len());
+            for mapping in batch {
+                let error_message = match (&fetched, quantities.get(&mapping.erp_item_id)) {
+                    (Ok(()), Some(&quantity)) => {
+                        changes.push((mapping, quantity));
+                        continue;
+                    }
+                    (Ok(()), None) => format!("Item {} not found in NetSuite", mapping.erp_item_id),
+                    (Err(e), _) => e.to_string(),
+                };
+
+                result.items_failed += 1;
+                result.errors.push(SyncItemError {
+                    item_id: mapping.erp_item_id.clone(),
+                    error_message,
+                    error_type: "fetch_failed".to_string(),
+                });
             }
-        }
-
-        // Update Atlas inventory
-        self.inventory_repo.update_quantity(inventory.id, inventory.quantity).await
-            .map_err(|e| SyncError::SyncFailed(format!("Failed to update inventory: {}", e)))?;
 
-        // Update last sync time
-        self.update_mapping_sync_time(mapping.id).await?;
+            result = result.combine(self.apply_erp_quantities(connection, &changes).await?);
+        }
 
-        Ok(())
+        Ok(result)
     }
 
     // ========================================================================
@@ -395,6 +627,8 @@ impl ErpSyncService {
         connection: &ErpConnection,
         inventory: &Inventory,
         mapping: &InventoryMapping,
+        fields: &ErpFieldMapping,
+        sync_log_id: Option<Uuid>,
     ) -> Result<()> {
         let config = connection.sap_config.as_ref()
             .ok_or_else(|| SyncError::SyncFailed("SAP config not available".to_string()))?;
@@ -402,13 +636,13 @@ impl ErpSyncService {
         let client = SapClient::new(config.clone())
             .map_err(|e| SyncError::SapError(e.to_string()))?;
 
-        let plant = config.plant.as_deref().unwrap_or("1000");
+        let plant = connection.sap_plant_for(mapping.erp_entity_id.as_deref());
         let storage_location = mapping.erp_location_id.as_deref().unwrap_or("0001");
 
         // Get current SAP stock
         let current_stock = client.get_material_stock(
             &mapping.erp_item_id,
-            plant,
+            &plant,
             storage_location,
         )
         .await
@@ -420,150 +654,1449 @@ impl ErpSyncService {
         // Only post goods movement if quantities differ
         if current_qty != atlas_qty {
             let quantity_delta = (atlas_qty - current_qty) as f64;
+            // NDC code would require fetching pharmaceutical details separately
+            let custom_fields = fields.lot_fields(&inventory.batch_number, &inventory.expiry_date.to_string());
+
+            if connection.sandbox_mode {
+                return self.record_sandbox_write(
+                    connection.id,
+                    sync_log_id,
+                    Some(mapping.id),
+                    &mapping.erp_item_id,
+                    Some(storage_location),
+                    "goods_movement",
+                    Some(serde_json::json!({ "plant": plant, "quantity": current_qty })),
+                    serde_json::json!({
+                        "plant": plant,
+                        "quantity": atlas_qty,
+                        "quantity_delta": quantity_delta,
+                        "unit": "PC",
+                        "custom_fields": custom_fields,
+                    }),
+                ).await;
+            }
 
             client.adjust_inventory(
                 &mapping.erp_item_id,
-                plant,
+                &plant,
                 storage_location,
                 quantity_delta,
                 "PC",  // Piece
-                Some(inventory.batch_number.clone()),
-                Some(inventory.expiry_date.to_string()),
-                None,  // NDC code would require fetching pharmaceutical details separately
+                custom_fields,
             )
             .await
             .map_err(|e| SyncError::SapError(e.to_string()))?;
         }
 
+        if connection.sandbox_mode {
+            return Ok(());
+        }
+
         // Update last sync time
         self.update_mapping_sync_time(mapping.id).await?;
 
         Ok(())
     }
 
-    async fn sync_from_sap(&self, connection: &ErpConnection) -> Result<SyncResult> {
+    /// Stock changed since the connection's OData delta token. Without a usable token (or
+    /// once SAP has discarded it) a change-tracked read of all plant stock runs instead,
+    /// issuing a fresh token; services without change tracking fall back to reading every
+    /// mapped item in batches. Pages are applied as they arrive.
+    async fn sync_from_sap(&self, connection: &ErpConnection, sync_log_id: Uuid) -> Result<SyncResult> {
+        let client = self.sap_client(connection)?;
+        let plants = connection.sap_plants();
+
+        let mut delta = None;
+        if let Some(token) = self.usable_delta_token(connection) {
+            match first_stock_page(client.material_stock_delta_pages(&plants, Some(token)).await).await {
+                Ok(read) => {
+                    self.mark_sync_log_incremental(sync_log_id).await?;
+                    delta = Some(read);
+                }
+                Err(SapError::DeltaTokenExpired) => {
+                    tracing::info!("SAP delta token expired for connection {}, running full sync", connection.id);
+                }
+                Err(e) => return Err(SyncError::SapError(e.to_string())),
+            }
+        }
+
+        let (mut pages, first) = match delta {
+            Some(read) => read,
+            None => match first_stock_page(client.material_stock_delta_pages(&plants, None).await).await {
+                Ok(read) => read,
+                Err(e) => {
+                    tracing::warn!(
+                        "SAP change tracking unavailable for connection {}, syncing in batches: {}",
+                        connection.id, e
+                    );
+                    self.connection_service.set_delta_token(connection.id, None).await
+                        .map_err(|e| SyncError::ConnectionError(e.to_string()))?;
+                    return self.sync_from_sap_full(connection).await;
+                }
+            },
+        };
+
+        let result = self.apply_sap_pages(connection, &plants, first, &mut pages).await?;
+
+        if result.items_failed == 0 {
+            if let Some(token) = pages.delta_token() {
+                self.save_delta_token(connection.id, token).await?;
+            }
+        }
+
+        Ok(result)
+    }
+
+    fn sap_client(&self, connection: &ErpConnection) -> Result<SapClient> {
         let config = connection.sap_config.as_ref()
             .ok_or_else(|| SyncError::SyncFailed("SAP config not available".to_string()))?;
 
-        let client = SapClient::new(config.clone())
-            .map_err(|e| SyncError::SapError(e.to_string()))?;
+        SapClient::new(config.clone())
+            .map_err(|e| SyncError::SapError(e.to_string()))
+    }
 
+    /// Apply changed SAP stock records page by page; unmapped records are ignored.
+    /// A record only reaches mappings of its own plant.
+    async fn apply_sap_pages(
+        &self,
+        connection: &ErpConnection,
+        plants: &[String],
+        first: Vec<MaterialStock>,
+        pages: &mut MaterialStockPages<'_>,
+    ) -> Result<SyncResult> {
         let mappings = self.get_mappings_for_connection(connection.id).await?;
+        let mut by_stock: HashMap<(&str, String, &str), Vec<&InventoryMapping>> = HashMap::new();
+        for mapping in &mappings {
+            let key = (
+                mapping.erp_item_id.as_str(),
+                connection.sap_plant_for(mapping.erp_entity_id.as_deref()),
+                mapping.erp_location_id.as_deref().unwrap_or("0001"),
+            );
+            by_stock.entry(key).or_default().push(mapping);
+        }
 
-        let mut result = SyncResult {
-            items_synced: 0,
-            items_failed: 0,
-            items_skipped: 0,
-            items_created: 0,
-            items_updated: 0,
-            conflicts_detected: 0,
-            errors: Vec::new(),
-        };
+        let mut result = SyncResult::default();
+        let mut page = Some(first);
+
+        while let Some(stock) = page {
+            let changes = stock
+                .iter()
+                .filter(|record| plants.contains(&record.plant))
+                .flat_map(|record| {
+                    let key = (record.material.as_str(), record.plant.clone(), record.storage_location.as_str());
+                    let quantity = sap_quantity(record);
+                    by_stock.get(&key)
+                        .into_iter()
+                        .flatten()
+                        .map(move |mapping| (*mapping, quantity))
+                })
+                .collect::<Vec<_>>();
+
+            result = result.combine(self.apply_erp_quantities(connection, &changes).await?);
+
+            page = pages.next_page().await
+                .map_err(|e| SyncError::SapError(e.to_string()))?;
+        }
 
-        let plant = config.plant.as_deref().unwrap_or("1000");
+        Ok(result)
+    }
 
-        for mapping in mappings {
-            if !mapping.sync_enabled {
-                result.items_skipped += 1;
-                continue;
-            }
+    /// Fetch the stock of every mapped item, a batch of materials of one plant per read
+    async fn sync_from_sap_full(&self, connection: &ErpConnection) -> Result<SyncResult> {
+        let client = self.sap_client(connection)?;
+        let mappings = self.get_mappings_for_connection(connection.id).await?;
 
-            let storage_location = mapping.erp_location_id.as_deref().unwrap_or("0001");
+        let mut by_plant: HashMap<String, Vec<&InventoryMapping>> = HashMap::new();
+        for mapping in &mappings {
+            by_plant.entry(connection.sap_plant_for(mapping.erp_entity_id.as_deref()))
+                .or_default()
+                .push(mapping);
+        }
 
-            match client.get_material_stock(&mapping.erp_item_id, plant, storage_location).await {
-                Ok(sap_stock) => {
-                    match self.update_atlas_from_sap(&mapping, &sap_stock, connection).await {
-                        Ok(_) => {
-                            result.items_synced += 1;
-                            result.items_updated += 1;
+        let mut result = SyncResult::default();
+
+        for (plant, plant_mappings) in by_plant {
+            let mut materials = plant_mappings.iter()
+                .map(|m| m.erp_item_id.clone())
+                .collect::<Vec<_>>();
+            materials.sort();
+            materials.dedup();
+
+            for batch in materials.chunks(bulk_sync::SAP_MATERIAL_BATCH_SIZE) {
+                let mut quantities: HashMap<(String, String), i32> = HashMap::new();
+                let fetched = match client.material_stock_pages(&plant, batch).await {
+                    Ok(mut pages) => loop {
+                        match pages.next_page().await {
+                            Ok(Some(stock)) => quantities.extend(stock.iter().map(|record| {
+                                ((record.material.clone(), record.storage_location.clone()), sap_quantity(record))
+                            })),
+                            Ok(None) => break Ok(()),
+                            Err(e) => break Err(e),
                         }
-                        Err(e) => {
-                            result.items_failed += 1;
-                            result.errors.push(SyncItemError {
-                                item_id: mapping.erp_item_id.clone(),
-                                error_message: e.to_string(),
-                                error_type: "update_failed".to_string(),
-                            });
+                    },
+                    Err(e) => Err(e),
+                };
+
+                let mut changes = Vec::new();
+                for mapping in plant_mappings.iter().filter(|m| batch.contains(&m.erp_item_id)) {
+                    let storage_location = mapping.erp_location_id.as_deref().unwrap_or("0001");
+                    let key = (mapping.erp_item_id.clone(), storage_location.to_string());
+
+                    let error_message = match (&fetched, quantities.get(&key)) {
+                        (Ok(()), Some(&quantity)) => {
+                            changes.push((*mapping, quantity));
+                            continue;
                         }
-                    }
-                }
-                Err(e) => {
+                        (Ok(()), None) => format!(
+                            "Material {} not found in plant {} / storage location {}",
+                            mapping.erp_item_id, plant, storage_location
+                        ),
+                        (Err(e), _) => e.to_string(),
+                    };
+
                     result.items_failed += 1;
                     result.errors.push(SyncItemError {
                         item_id: mapping.erp_item_id.clone(),
-                        error_message: e.to_string(),
+                        error_message,
                         error_type: "fetch_failed".to_string(),
                     });
                 }
+
+                result = result.combine(self.apply_erp_quantities(connection, &changes).await?);
             }
         }
 
         Ok(result)
     }
 
-    async fn update_atlas_from_sap(
+    // ========================================================================
+    // Sandbox Mode
+    // ========================================================================
+
+    /// Capture the NetSuite writes of `sync_to_netsuite` against the item's current values
+    #[allow(clippy::too_many_arguments)]
+    async fn capture_netsuite_writes(
         &self,
-        mapping: &InventoryMapping,
-        sap_stock: &crate::services::erp::sap_client::MaterialStock,
+        client: &NetSuiteClient,
         connection: &ErpConnection,
+        inventory: &Inventory,
+        mapping: &InventoryMapping,
+        fields: &ErpFieldMapping,
+        location_id: &str,
+        sync_log_id: Option<Uuid>,
     ) -> Result<()> {
-        let mut inventory = self.inventory_repo.find_by_id(mapping.atlas_inventory_id).await
-            .map_err(|e| SyncError::SyncFailed(format!("Failed to get inventory: {}", e)))?
-            .ok_or_else(|| SyncError::SyncFailed(format!("Inventory {} not found", mapping.atlas_inventory_id)))?;
+        let current = client.get_inventory_item(&mapping.erp_item_id).await
+            .map_err(|e| SyncError::NetSuiteError(e.to_string()))?;
 
-        let sap_quantity = sap_stock.stock_quantity.parse::<i32>().unwrap_or(0);
+        let current_quantity = current.locations.as_ref()
+            .and_then(|locations| locations.items.iter().find(|l| l.location.id == location_id))
+            .and_then(|l| l.quantity_on_hand)
+            .or(current.quantity_on_hand);
 
-        // Check for conflicts
-        if inventory.quantity != sap_quantity {
-            match connection.conflict_resolution {
-                crate::services::erp::erp_connection_service::ConflictResolution::ErpWins => {
-                    inventory.quantity = sap_quantity;
-                }
-                crate::services::erp::erp_connection_service::ConflictResolution::AtlasWins => {
-                    return Ok(());
-                }
-                crate::services::erp::erp_connection_service::ConflictResolution::Manual => {
-                    self.create_conflict_record(mapping, "quantity_mismatch").await?;
-                    return Ok(());
-                }
-                crate::services::erp::erp_connection_service::ConflictResolution::LatestTimestamp => {
-                    inventory.quantity = sap_quantity;
-                }
-            }
+        self.record_sandbox_write(
+            connection.id,
+            sync_log_id,
+            Some(mapping.id),
+            &mapping.erp_item_id,
+            Some(location_id),
+            "update_quantity",
+            Some(serde_json::json!({ "quantity": current_quantity })),
+            serde_json::json!({ "quantity": inventory.quantity as f64 }),
+        ).await?;
+
+        if connection.sync_lot_batch {
+            let custom_fields = fields.lot_fields(&inventory.batch_number, &inventory.expiry_date.to_string());
+            let current_fields: HashMap<&String, Option<String>> = custom_fields
+                .keys()
+                .map(|field| (field, current.custom_field(Some(field.as_str()))))
+                .collect();
+
+            self.record_sandbox_write(
+                connection.id,
+                sync_log_id,
+                Some(mapping.id),
+                &mapping.erp_item_id,
+                None,
+                "update_custom_fields",
+                Some(serde_json::json!(current_fields)),
+                serde_json::json!(custom_fields),
+            ).await?;
         }
 
-        // Update Atlas inventory
-        self.inventory_repo.update_quantity(inventory.id, inventory.quantity).await
-            .map_err(|e| SyncError::SyncFailed(format!("Failed to update inventory: {}", e)))?;
+        Ok(())
+    }
 
-        self.update_mapping_sync_time(mapping.id).await?;
+    #[allow(clippy::too_many_arguments)]
+    async fn record_sandbox_write(
+        &self,
+        connection_id: Uuid,
+        sync_log_id: Option<Uuid>,
+        mapping_id: Option<Uuid>,
+        erp_item_id: &str,
+        erp_location_id: Option<&str>,
+        operation: &str,
+        current_value: Option<serde_json::Value>,
+        proposed_value: serde_json::Value,
+    ) -> Result<()> {
+        tracing::debug!("Sandbox: captured {} for {} on connection {}", operation, erp_item_id, connection_id);
+
+        sqlx::query(
+            r#"
+            INSERT INTO erp_sandbox_writes (
+                erp_connection_id, erp_sync_log_id, erp_mapping_id, erp_item_id, erp_location_id,
+                operation, current_value, proposed_value
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#
+        )
+        .bind(connection_id)
+        .bind(sync_log_id)
+        .bind(mapping_id)
+        .bind(erp_item_id)
+        .bind(erp_location_id)
+        .bind(operation)
+        .bind(current_value)
+        .bind(proposed_value)
+        .execute(&self.db_pool)
+        .await?;
 
         Ok(())
     }
 
     // ========================================================================
-    // Helper Methods
+    // Conflict Review (see ErpConflictService)
     // ========================================================================
 
-    async fn sync_single_item_to_erp(
-        &self,
-        connection: &ErpConnection,
-        inventory: &Inventory,
-    ) -> Result<()> {
-        let mapping = self.get_or_create_mapping(connection, inventory).await?;
+    /// Set the Atlas quantity of a mapped item
+    pub async fn write_atlas_quantity(&self, mapping_id: Uuid, quantity: i32) -> Result<()> {
+        let mapping = self.get_mapping_by_id(mapping_id).await?;
 
-        if !mapping.sync_enabled {
-            return Ok(());
-        }
+        self.inventory_repo.update_quantity(mapping.atlas_inventory_id, quantity).await
+            .map_err(|e| SyncError::SyncFailed(format!("Failed to update inventory: {}", e)))?;
+
+        self.update_mapping_sync_time(mapping.id).await
+    }
+
+    /// Write a quantity for a mapped item to the ERP, leaving Atlas untouched. Flat-file
+    /// connections have no write API; their next export carries the Atlas quantity.
+    pub async fn write_erp_quantity(&self, mapping_id: Uuid, quantity: i32) -> Result<()> {
+        let mapping = self.get_mapping_by_id(mapping_id).await?;
+
+        let connection = self.connection_service
+            .get_connection_by_id(mapping.erp_connection_id)
+            .await
+            .map_err(|e| SyncError::ConnectionError(e.to_string()))?;
+
+        let mut inventory = self.inventory_repo.find_by_id(mapping.atlas_inventory_id).await
+            .map_err(|e| SyncError::SyncFailed(format!("Failed to get inventory: {}", e)))?
+            .ok_or_else(|| SyncError::SyncFailed(format!("Inventory {} not found", mapping.atlas_inventory_id)))?;
+        inventory.quantity = quantity;
 
+        let fields = self.field_mapping_for(&connection).await?;
         match connection.erp_type {
-            ErpType::NetSuite => self.sync_to_netsuite(connection, inventory, &mapping).await,
-            ErpType::SapS4Hana => self.sync_to_sap(connection, inventory, &mapping).await,
+            ErpType::NetSuite => self.sync_to_netsuite(&connection, &inventory, &mapping, &fields, None).await,
+            ErpType::SapS4Hana => self.sync_to_sap(&connection, &inventory, &mapping, &fields, None).await,
+            ErpType::FlatFile => Ok(()),
         }
     }
 
-    async fn get_or_create_mapping(
-        &self,
+    async fn get_mapping_by_id(&self, mapping_id: Uuid) -> Result<InventoryMapping> {
+        use sqlx::Row;
+
+        let row = sqlx::query(
+            r#"
+            SELECT id, erp_connection_id, atlas_inventory_id, erp_item_id, erp_location_id, erp_entity_id, sync_enabled
+            FROM erp_inventory_mappings
+            WHERE id = $1
+            "#
+        )
+        .bind(mapping_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| SyncError::SyncFailed(format!("Mapping {} not found", mapping_id)))?;
+
+        Ok(InventoryMapping {
+            id: row.get("id"),
+            erp_connection_id: row.get("erp_connection_id"),
+            atlas_inventory_id: row.get("atlas_inventory_id"),
+            erp_item_id: row.get("erp_item_id"),
+            erp_location_id: row.get("erp_location_id"),
+            erp_entity_id: row.get("erp_entity_id"),
+            sync_enabled: row.get("sync_enabled"),
+        })
+    }
+
+    // ========================================================================
+    // Delta Sync
+    // ========================================================================
+
+    /// The connection's delta cursor, unless it is older than `DELTA_TOKEN_MAX_AGE_DAYS`
+    fn usable_delta_token<'a>(&self, connection: &'a ErpConnection) -> Option<&'a str> {
+        let issued_at = connection.delta_token_updated_at?;
+        if Utc::now() - issued_at > chrono::Duration::days(DELTA_TOKEN_MAX_AGE_DAYS) {
+            return None;
+        }
+        connection.delta_token.as_deref()
+    }
+
+    async fn save_delta_token(&self, connection_id: Uuid, token: &str) -> Result<()> {
+        self.connection_service.set_delta_token(connection_id, Some(token)).await
+            .map_err(|e| SyncError::ConnectionError(e.to_string()))
+    }
+
+    async fn mark_sync_log_incremental(&self, sync_log_id: Uuid) -> Result<()> {
+        sqlx::query("UPDATE erp_sync_logs SET sync_type = 'incremental' WHERE id = $1")
+            .bind(sync_log_id)
+            .execute(&self.db_pool)
+            .await?;
+
+        Ok(())
+    }
+
+    // ========================================================================
+    // Marketplace Orders (Atlas -> ERP)
+    // ========================================================================
+
+    /// Export a completed transaction to the parties' ERPs: a sales order for the seller
+    /// and a purchase order for the buyer, on connections with `sync_transactions` and
+    /// the matching export toggle. Orders already created are returned as they are; a
+    /// failed side is recorded on its export and does not stop the other.
+    pub async fn export_transaction_orders(&self, transaction_id: Uuid) -> Result<Vec<OrderExport>> {
+        let source = sqlx::query_as::<_, OrderSource>(
+            r#"
+            SELECT
+                t.id AS transaction_id, t.seller_id, t.buyer_id, t.quantity, t.unit_price,
+                t.status, t.transaction_date, i.inventory_id, inv.pharmaceutical_id
+            FROM transactions t
+            JOIN inquiries i ON i.id = t.inquiry_id
+            JOIN inventory inv ON inv.id = i.inventory_id
+            WHERE t.id = $1
+            "#
+        )
+        .bind(transaction_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| SyncError::SyncFailed(format!("Transaction {} not found", transaction_id)))?;
+
+        if source.status.as_deref() != Some("completed") {
+            return Err(SyncError::SyncFailed(format!("Transaction {} is not completed", transaction_id)));
+        }
+
+        let mut exports = Vec::new();
+        for side in [OrderSide::Sales, OrderSide::Purchase] {
+            if let Some(export) = self.export_order(&source, side).await? {
+                exports.push(export);
+            }
+        }
+
+        Ok(exports)
+    }
+
+    /// Order exports of a transaction on the user's own connections
+    pub async fn get_order_exports(&self, transaction_id: Uuid, user_id: Uuid) -> Result<Vec<OrderExport>> {
+        let exports = sqlx::query_as::<_, OrderExport>(
+            r#"
+            SELECT e.id, e.transaction_id, e.erp_connection_id, e.order_type, e.status,
+                   e.erp_document_id, e.erp_document_number, e.attempts, e.error_message,
+                   e.created_at, e.updated_at
+            FROM erp_order_exports e
+            JOIN erp_connections c ON c.id = e.erp_connection_id
+            WHERE e.transaction_id = $1 AND c.user_id = $2
+            ORDER BY e.order_type
+            "#
+        )
+        .bind(transaction_id)
+        .bind(user_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(exports)
+    }
+
+    async fn export_order(&self, source: &OrderSource, side: OrderSide) -> Result<Option<OrderExport>> {
+        let user_id = match side {
+            OrderSide::Sales => source.seller_id,
+            OrderSide::Purchase => source.buyer_id,
+        };
+
+        let connection = match self.connection_service.get_active_connection_for_user(user_id).await {
+            Ok(connection) => connection,
+            Err(crate::services::erp::erp_connection_service::ErpConnectionError::NotFound(_)) => return Ok(None),
+            Err(e) => return Err(SyncError::ConnectionError(e.to_string())),
+        };
+
+        let enabled = match side {
+            OrderSide::Sales => connection.export_sales_orders,
+            OrderSide::Purchase => connection.export_purchase_orders,
+        };
+        if !connection.sync_transactions || !enabled || connection.erp_type == ErpType::FlatFile {
+            return Ok(None);
+        }
+
+        let export = match self.claim_order_export(source.transaction_id, connection.id, side).await? {
+            Some(export) => export,
+            // Already created in the ERP
+            None => return self.find_order_export(source.transaction_id, side).await,
+        };
+
+        let export = match self.send_order(&connection, source, side, export.id).await {
+            Ok(OrderOutcome::Created { document_id, document_number }) => {
+                tracing::info!(
+                    "Created ERP {} {} for transaction {}",
+                    side.order_type(), document_number, source.transaction_id
+                );
+                self.complete_order_export(export.id, source.transaction_id, side, &document_id, &document_number).await?
+            }
+            Ok(OrderOutcome::Sandbox) => self.set_order_export_status(export.id, "sandbox", None).await?,
+            Err(e) => {
+                tracing::warn!(
+                    "ERP {} export failed for transaction {}: {}",
+                    side.order_type(), source.transaction_id, e
+                );
+                self.set_order_export_status(export.id, "failed", Some(&e.to_string())).await?
+            }
+        };
+
+        Ok(Some(export))
+    }
+
+    async fn send_order(
+        &self,
+        connection: &ErpConnection,
+        source: &OrderSource,
+        side: OrderSide,
+        idempotency_key: Uuid,
+    ) -> Result<OrderOutcome> {
+        let mapping = self.get_order_item_mapping(connection.id, source).await?
+            .ok_or_else(|| SyncError::SyncFailed(format!(
+                "No ERP item mapped for pharmaceutical {}", source.pharmaceutical_id
+            )))?;
+
+        let counterpart = match side {
+            OrderSide::Sales => connection.order_customer_id.as_deref(),
+            OrderSide::Purchase => connection.order_vendor_id.as_deref(),
+        }
+        .ok_or_else(|| SyncError::SyncFailed(format!(
+            "No ERP {} configured for marketplace orders",
+            if side == OrderSide::Sales { "customer" } else { "vendor" }
+        )))?;
+
+        let reference = format!("ATLAS-{}", source.transaction_id);
+        let key = idempotency_key.to_string();
+        let operation = match side {
+            OrderSide::Sales => "create_sales_order",
+            OrderSide::Purchase => "create_purchase_order",
+        };
+
+        match connection.erp_type {
+            ErpType::NetSuite => {
+                use rust_decimal::prelude::ToPrimitive;
+
+                let config = connection.netsuite_config.as_ref()
+                    .ok_or_else(|| SyncError::SyncFailed("NetSuite config not available".to_string()))?;
+
+                let mut line = serde_json::json!({
+                    "item": { "id": mapping.erp_item_id },
+                    "quantity": source.quantity,
+                    "rate": source.unit_price.to_f64().unwrap_or(0.0),
+                });
+                if let Some(location) = &mapping.erp_location_id {
+                    line["location"] = serde_json::json!({ "id": location });
+                }
+
+                let payload = serde_json::json!({
+                    "externalId": reference,
+                    "entity": { "id": counterpart },
+                    "tranDate": source.transaction_date.unwrap_or_else(Utc::now).format("%Y-%m-%d").to_string(),
+                    "memo": format!("Atlas marketplace transaction {}", source.transaction_id),
+                    "item": { "items": [line] },
+                });
+
+                if connection.sandbox_mode {
+                    self.record_sandbox_write(
+                        connection.id, None, Some(mapping.id), &mapping.erp_item_id,
+                        mapping.erp_location_id.as_deref(), operation, None, payload,
+                    ).await?;
+                    return Ok(OrderOutcome::Sandbox);
+                }
+
+                let client = NetSuiteClient::new(config.clone())
+                    .map_err(|e| SyncError::NetSuiteError(e.to_string()))?;
+
+                let (record_type, created) = match side {
+                    OrderSide::Sales => ("salesOrder", client.create_sales_order(&payload, &key).await),
+                    OrderSide::Purchase => ("purchaseOrder", client.create_purchase_order(&payload, &key).await),
+                };
+                let document_id = created.map_err(|e| SyncError::NetSuiteError(e.to_string()))?;
+
+                let document_number = client.get_transaction_number(record_type, &document_id).await
+                    .map_err(|e| SyncError::NetSuiteError(e.to_string()))?;
+
+                Ok(OrderOutcome::Created { document_id, document_number })
+            }
+            ErpType::SapS4Hana => {
+                let config = connection.sap_config.as_ref()
+                    .ok_or_else(|| SyncError::SyncFailed("SAP config not available".to_string()))?;
+
+                // The mapped plant books the order, under its own company code if it has one
+                let entity = mapping.erp_entity_id.as_deref().and_then(|id| connection.entity(id));
+                let organization = entity
+                    .and_then(|e| e.company_code.clone())
+                    .or_else(|| config.company_code.clone())
+                    .unwrap_or_else(|| "1000".to_string());
+                let plant = connection.sap_plant_for(mapping.erp_entity_id.as_deref());
+
+                let client = SapClient::new(config.clone())
+                    .map_err(|e| SyncError::SapError(e.to_string()))?;
+
+                let created = match side {
+                    OrderSide::Sales => {
+                        let order = SalesOrder {
+                            sales_order_type: "OR".to_string(),
+                            sales_organization: organization,
+                            distribution_channel: "10".to_string(),
+                            organization_division: "00".to_string(),
+                            sold_to_party: counterpart.to_string(),
+                            purchase_order_by_customer: reference,
+                            to_item: SalesOrderItems {
+                                results: vec![SalesOrderItem {
+                                    material: mapping.erp_item_id.clone(),
+                                    requested_quantity: source.quantity.to_string(),
+                                    requested_quantity_unit: "PC".to_string(),
+                                }],
+                            },
+                        };
+
+                        if connection.sandbox_mode {
+                            let proposed = serde_json::to_value(&order)
+                                .map_err(|e| SyncError::SyncFailed(e.to_string()))?;
+                            self.record_sandbox_write(
+                                connection.id, None, Some(mapping.id), &mapping.erp_item_id,
+                                None, operation, None, proposed,
+                            ).await?;
+                            return Ok(OrderOutcome::Sandbox);
+                        }
+
+                        client.create_sales_order(order, &key).await
+                    }
+                    OrderSide::Purchase => {
+                        let order = PurchaseOrder {
+                            purchase_order_type: "NB".to_string(),
+                            company_code: organization.clone(),
+                            purchasing_organization: organization,
+                            purchasing_group: "001".to_string(),
+                            supplier: counterpart.to_string(),
+                            document_currency: "USD".to_string(),
+                            to_purchase_order_item: PurchaseOrderItems {
+                                results: vec![PurchaseOrderItem {
+                                    material: mapping.erp_item_id.clone(),
+                                    plant,
+                                    order_quantity: source.quantity.to_string(),
+                                    purchase_order_quantity_unit: "PC".to_string(),
+                                    net_price_amount: source.unit_price.to_string(),
+                                }],
+                            },
+                        };
+
+                        if connection.sandbox_mode {
+                            let proposed = serde_json::to_value(&order)
+                                .map_err(|e| SyncError::SyncFailed(e.to_string()))?;
+                            self.record_sandbox_write(
+                                connection.id, None, Some(mapping.id), &mapping.erp_item_id,
+                                None, operation, None, proposed,
+                            ).await?;
+                            return Ok(OrderOutcome::Sandbox);
+                        }
+
+                        client.create_purchase_order(order, &key).await
+                    }
+                };
+
+                // SAP returns the document number as the key
+                let document_number = created.map_err(|e| SyncError::SapError(e.to_string()))?;
+                Ok(OrderOutcome::Created { document_id: document_number.clone(), document_number })
+            }
+            ErpType::FlatFile => Err(SyncError::SyncFailed(
+                "Flat-file connections do not support order export".to_string(),
+            )),
+        }
+    }
+
+    /// ERP item for the traded product on a connection: the seller's mapping of the sold
+    /// inventory, otherwise the most recently synced mapping of the same pharmaceutical
+    async fn get_order_item_mapping(&self, connection_id: Uuid, source: &OrderSource) -> Result<Option<InventoryMapping>> {
+        use sqlx::Row;
+
+        let row = sqlx::query(
+            r#"
+            SELECT m.id, m.erp_connection_id, m.atlas_inventory_id, m.erp_item_id, m.erp_location_id, m.erp_entity_id, m.sync_enabled
+            FROM erp_inventory_mappings m
+            JOIN inventory inv ON inv.id = m.atlas_inventory_id
+            WHERE m.erp_connection_id = $1 AND inv.pharmaceutical_id = $2
+            ORDER BY (m.atlas_inventory_id = $3) DESC, m.last_synced_at DESC NULLS LAST
+            LIMIT 1
+            "#
+        )
+        .bind(connection_id)
+        .bind(source.pharmaceutical_id)
+        .bind(source.inventory_id)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        Ok(row.map(|r| InventoryMapping {
+            id: r.get("id"),
+            erp_connection_id: r.get("erp_connection_id"),
+            atlas_inventory_id: r.get("atlas_inventory_id"),
+            erp_item_id: r.get("erp_item_id"),
+            erp_location_id: r.get("erp_location_id"),
+            erp_entity_id: r.get("erp_entity_id"),
+            sync_enabled: r.get("sync_enabled"),
+        }))
+    }
+
+    /// Start (or retry) the export of one side of a transaction. Returns None when the
+    /// order was already created.
+    async fn claim_order_export(
+        &self,
+        transaction_id: Uuid,
+        connection_id: Uuid,
+        side: OrderSide,
+    ) -> Result<Option<OrderExport>> {
+        let export = sqlx::query_as::<_, OrderExport>(
+            r#"
+            INSERT INTO erp_order_exports (transaction_id, erp_connection_id, order_type, attempts)
+            VALUES ($1, $2, $3, 1)
+            ON CONFLICT (transaction_id, order_type) DO UPDATE
+            SET erp_connection_id = EXCLUDED.erp_connection_id,
+                status = 'pending',
+                attempts = erp_order_exports.attempts + 1,
+                error_message = NULL,
+                updated_at = NOW()
+            WHERE erp_order_exports.status <> 'created'
+            RETURNING id, transaction_id, erp_connection_id, order_type, status,
+                      erp_document_id, erp_document_number, attempts, error_message,
+                      created_at, updated_at
+            "#
+        )
+        .bind(transaction_id)
+        .bind(connection_id)
+        .bind(side.order_type())
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        Ok(export)
+    }
+
+    async fn find_order_export(&self, transaction_id: Uuid, side: OrderSide) -> Result<Option<OrderExport>> {
+        let export = sqlx::query_as::<_, OrderExport>(
+            r#"
+            SELECT id, transaction_id, erp_connection_id, order_type, status,
+                   erp_document_id, erp_document_number, attempts, error_message,
+                   created_at, updated_at
+            FROM erp_order_exports
+            WHERE transaction_id = $1 AND order_type = $2
+            "#
+        )
+        .bind(transaction_id)
+        .bind(side.order_type())
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        Ok(export)
+    }
+
+    async fn set_order_export_status(&self, export_id: Uuid, status: &str, error_message: Option<&str>) -> Result<OrderExport> {
+        let export = sqlx::query_as::<_, OrderExport>(
+            r#"
+            UPDATE erp_order_exports
+            SET status = $2, error_message = $3, updated_at = NOW()
+            WHERE id = $1
+            RETURNING id, transaction_id, erp_connection_id, order_type, status,
+                      erp_document_id, erp_document_number, attempts, error_message,
+                      created_at, updated_at
+            "#
+        )
+        .bind(export_id)
+        .bind(status)
+        .bind(error_message)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        Ok(export)
+    }
+
+    /// Mark an export created and write the ERP document number back onto the transaction
+    async fn complete_order_export(
+        &self,
+        export_id: Uuid,
+        transaction_id: Uuid,
+        side: OrderSide,
+        document_id: &str,
+        document_number: &str,
+    ) -> Result<OrderExport> {
+        let mut tx = self.db_pool.begin().await?;
+
+        let export = sqlx::query_as::<_, OrderExport>(
+            r#"
+            UPDATE erp_order_exports
+            SET status = 'created', erp_document_id = $2, erp_document_number = $3,
+                error_message = NULL, updated_at = NOW()
+            WHERE id = $1
+            RETURNING id, transaction_id, erp_connection_id, order_type, status,
+                      erp_document_id, erp_document_number, attempts, error_message,
+                      created_at, updated_at
+            "#
+        )
+        .bind(export_id)
+        .bind(document_id)
+        .bind(document_number)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let write_back = match side {
+            OrderSide::Sales => "UPDATE transactions SET erp_sales_order_number = $2 WHERE id = $1",
+            OrderSide::Purchase => "UPDATE transactions SET erp_purchase_order_number = $2 WHERE id = $1",
+        };
+        sqlx::query(write_back)
+            .bind(transaction_id)
+            .bind(document_number)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(export)
+    }
+
+    // ========================================================================
+    // Webhook Events (real-time ERP -> Atlas)
+    // ========================================================================
+
+    /// Apply a stock event pushed by the connection's ERP. Mapped items update Atlas
+    /// inventory (subject to the connection's conflict resolution); unmapped items are
+    /// queued in `erp_pending_mappings`. Each event is recorded as a 'webhook' sync log.
+    pub async fn process_webhook_event(&self, connection_id: Uuid, event: &WebhookEvent) -> Result<SyncResult> {
+        let connection = self.connection_service
+            .get_connection_by_id(connection_id)
+            .await
+            .map_err(|e| SyncError::ConnectionError(e.to_string()))?;
+
+        let (sync_log_id, _in_flight) = self.create_sync_log(&connection, "erp_to_atlas", "webhook").await?;
+        let start_time = Utc::now();
+
+        let result = self.apply_webhook_items(&connection, event).await;
+
+        let duration = (Utc::now() - start_time).num_seconds() as i32;
+        self.complete_sync_log(sync_log_id, &result, duration).await?;
+
+        result
+    }
+
+    async fn apply_webhook_items(&self, connection: &ErpConnection, event: &WebhookEvent) -> Result<SyncResult> {
+        let mut result = SyncResult::default();
+        // A mapping without a location takes the first location an event reports,
+        // like the polling sync does
+        let mut applied_mappings = HashSet::new();
+
+        for item in &event.items {
+            let Some((mapping, direction)) = self.get_mapping_for_webhook_item(connection.id, item).await? else {
+                self.record_pending_mapping(connection.id, &event.event_type, item).await?;
+                result.items_skipped += 1;
+                continue;
+            };
+
+            let pulls_from_erp = matches!(direction.as_str(), "erp_to_atlas" | "bidirectional");
+            let quantity = match item.quantity {
+                Some(quantity) if mapping.sync_enabled && pulls_from_erp && applied_mappings.insert(mapping.id) => quantity,
+                _ => {
+                    result.items_skipped += 1;
+                    continue;
+                }
+            };
+
+            match self.apply_erp_quantity(connection, &mapping, quantity).await {
+                Ok(QuantityOutcome::Updated) => {
+                    result.items_synced += 1;
+                    result.items_updated += 1;
+                }
+                Ok(QuantityOutcome::Kept) => result.items_skipped += 1,
+                Ok(QuantityOutcome::Conflict) => result.conflicts_detected += 1,
+                Err(e) => {
+                    result.items_failed += 1;
+                    result.errors.push(SyncItemError {
+                        item_id: item.item_id.clone(),
+                        error_message: e.to_string(),
+                        error_type: "update_failed".to_string(),
+                    });
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// The mapping of a webhook item in any direction, preferring an exact location
+    /// match over a mapping without location
+    async fn get_mapping_for_webhook_item(
+        &self,
+        connection_id: Uuid,
+        item: &ErpItemUpdate,
+    ) -> Result<Option<(InventoryMapping, String)>> {
+        use sqlx::Row;
+
+        let row = sqlx::query(
+            r#"
+            SELECT id, erp_connection_id, atlas_inventory_id, erp_item_id, erp_location_id,
+                   erp_entity_id, sync_enabled, sync_direction
+            FROM erp_inventory_mappings
+            WHERE erp_connection_id = $1 AND erp_item_id = $2
+              AND (erp_location_id IS NOT DISTINCT FROM $3 OR erp_location_id IS NULL)
+            ORDER BY (erp_location_id IS NOT DISTINCT FROM $3) DESC
+            LIMIT 1
+            "#
+        )
+        .bind(connection_id)
+        .bind(&item.item_id)
+        .bind(item.location_id.as_deref())
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        Ok(row.map(|r| (
+            InventoryMapping {
+                id: r.get("id"),
+                erp_connection_id: r.get("erp_connection_id"),
+                atlas_inventory_id: r.get("atlas_inventory_id"),
+                erp_item_id: r.get("erp_item_id"),
+                erp_location_id: r.get("erp_location_id"),
+                erp_entity_id: r.get("erp_entity_id"),
+                sync_enabled: r.get("sync_enabled"),
+            },
+            r.get("sync_direction"),
+        )))
+    }
+
+    /// Queue an unmapped ERP item for the user to map (or refresh it if already queued)
+    async fn record_pending_mapping(&self, connection_id: Uuid, event_type: &str, item: &ErpItemUpdate) -> Result<()> {
+        let item_name = item.item_name.as_ref().map(|name| name.chars().take(255).collect::<String>());
+        let ndc_code = item.ndc_code.as_ref().map(|ndc| ndc.chars().take(50).collect::<String>());
+
+        sqlx::query(
+            r#"
+            INSERT INTO erp_pending_mappings (
+                erp_connection_id, erp_item_id, erp_location_id, erp_item_name, ndc_code,
+                last_quantity, last_event_type
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (erp_connection_id, erp_item_id, (COALESCE(erp_location_id, ''))) DO UPDATE
+            SET erp_item_name = COALESCE(EXCLUDED.erp_item_name, erp_pending_mappings.erp_item_name),
+                ndc_code = COALESCE(EXCLUDED.ndc_code, erp_pending_mappings.ndc_code),
+                last_quantity = COALESCE(EXCLUDED.last_quantity, erp_pending_mappings.last_quantity),
+                last_event_type = EXCLUDED.last_event_type,
+                occurrences = erp_pending_mappings.occurrences + 1,
+                last_seen_at = NOW()
+            "#
+        )
+        .bind(connection_id)
+        .bind(&item.item_id)
+        .bind(item.location_id.as_deref())
+        .bind(item_name)
+        .bind(ndc_code)
+        .bind(item.quantity)
+        .bind(event_type)
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    // ========================================================================
+    // Flat-File (CSV over SFTP) Sync Implementation
+    // ========================================================================
+
+    async fn import_flat_files(&self, connection: &ErpConnection, triggered_by: &str) -> Result<SyncResult> {
+        let (sync_log_id, _in_flight) = self.create_sync_log(connection, "erp_to_atlas", triggered_by).await?;
+        let start_time = Utc::now();
+
+        let result = self.sync_from_flat_file(connection, sync_log_id).await;
+
+        let duration = (Utc::now() - start_time).num_seconds() as i32;
+        self.complete_sync_log(sync_log_id, &result, duration).await?;
+
+        result
+    }
+
+    /// Import every new file in the inbound directory. Files whose content was already
+    /// imported (same SHA-256) are recorded as duplicates and not applied again.
+    async fn sync_from_flat_file(&self, connection: &ErpConnection, sync_log_id: Uuid) -> Result<SyncResult> {
+        let client = self.sftp_client(connection)?;
+        let mapping = connection.column_mapping.clone().unwrap_or_default();
+
+        let files = client.list_inbound().await
+            .map_err(|e| SyncError::SftpError(e.to_string()))?;
+
+        let mut result = SyncResult::default();
+
+        for file in files {
+            if file.size > MAX_FILE_BYTES {
+                let message = format!("File exceeds {} MB", MAX_FILE_BYTES / 1024 / 1024);
+                self.record_file_error(connection, sync_log_id, &file, None, &mut result, message).await?;
+                continue;
+            }
+
+            let contents = match client.download(&file.path).await {
+                Ok(contents) => contents,
+                Err(e) => {
+                    self.record_file_error(connection, sync_log_id, &file, None, &mut result, e.to_string()).await?;
+                    continue;
+                }
+            };
+            let checksum = checksum_sha256(&contents);
+
+            if let Some(processed_as) = self.find_processed_inbound_file(connection.id, &checksum).await? {
+                // Without an archive directory imported files stay put; only re-deliveries count
+                if processed_as == file.name && client.config().archive_path.is_none() {
+                    continue;
+                }
+
+                tracing::info!(
+                    "Skipping duplicate flat file {} for connection {} (same content as {})",
+                    file.name, connection.id, processed_as
+                );
+                self.record_transfer(connection.id, sync_log_id, "inbound", &file.name, contents.len(), Some(&checksum), "duplicate", 0, 0, None).await?;
+                result.items_skipped += 1;
+                if let Err(e) = client.archive(&file.path, &file.name).await {
+                    tracing::warn!("Failed to archive duplicate file {}: {}", file.name, e);
+                }
+                continue;
+            }
+
+            let parsed = match parse_csv(&contents, &mapping) {
+                Ok(parsed) => parsed,
+                Err(message) => {
+                    // Left in place so it is retried once the column mapping is fixed
+                    self.record_file_error(connection, sync_log_id, &file, Some(&checksum), &mut result, message).await?;
+                    continue;
+                }
+            };
+
+            let rows_total = (parsed.rows.len() + parsed.errors.len()) as i32;
+            let mut rows_failed = parsed.errors.len() as i32;
+
+            for error in parsed.errors {
+                result.items_failed += 1;
+                result.errors.push(SyncItemError {
+                    item_id: format!("{}:{}", file.name, error.line),
+                    error_message: error.message,
+                    error_type: "parse_failed".to_string(),
+                });
+            }
+
+            for row in parsed.rows {
+                match self.apply_flat_file_row(connection, &row).await {
+                    Ok(true) => {
+                        result.items_synced += 1;
+                        result.items_updated += 1;
+                    }
+                    Ok(false) => {
+                        result.items_skipped += 1;
+                    }
+                    Err(e) => {
+                        rows_failed += 1;
+                        result.items_failed += 1;
+                        result.errors.push(SyncItemError {
+                            item_id: row.item_id.clone(),
+                            error_message: e.to_string(),
+                            error_type: "update_failed".to_string(),
+                        });
+                    }
+                }
+            }
+
+            self.record_transfer(connection.id, sync_log_id, "inbound", &file.name, contents.len(), Some(&checksum), "processed", rows_total, rows_failed, None).await?;
+
+            if let Err(e) = client.archive(&file.path, &file.name).await {
+                tracing::warn!("Failed to archive flat file {}: {}", file.name, e);
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Apply one stock row through the connection's inventory mappings.
+    /// Returns false when the row has no (enabled) mapping.
+    async fn apply_flat_file_row(&self, connection: &ErpConnection, row: &FlatFileRow) -> Result<bool> {
+        let Some(mapping) = self
+            .get_mapping_by_erp_item(connection.id, &row.item_id, row.location_id.as_deref())
+            .await?
+        else {
+            tracing::debug!("No mapping for flat-file item {} on connection {}", row.item_id, connection.id);
+            return Ok(false);
+        };
+
+        if !mapping.sync_enabled {
+            return Ok(false);
+        }
+
+        self.apply_erp_quantity(connection, &mapping, row.quantity).await?;
+
+        Ok(true)
+    }
+
+    /// Write an ERP quantity to the mapped Atlas inventory, following the
+    /// connection's conflict resolution when the two differ
+    async fn apply_erp_quantity(
+        &self,
+        connection: &ErpConnection,
+        mapping: &InventoryMapping,
+        erp_quantity: i32,
+    ) -> Result<QuantityOutcome> {
+        let mut inventory = self.inventory_repo.find_by_id(mapping.atlas_inventory_id).await
+            .map_err(|e| SyncError::SyncFailed(format!("Failed to get inventory: {}", e)))?
+            .ok_or_else(|| SyncError::SyncFailed(format!("Inventory {} not found", mapping.atlas_inventory_id)))?;
+
+        // Check for conflicts
+        if inventory.quantity != erp_quantity {
+            match connection.conflict_resolution {
+                crate::services::erp::erp_connection_service::ConflictResolution::ErpWins => {
+                    inventory.quantity = erp_quantity;
+                }
+                crate::services::erp::erp_connection_service::ConflictResolution::AtlasWins => {
+                    return Ok(QuantityOutcome::Kept);
+                }
+                crate::services::erp::erp_connection_service::ConflictResolution::Manual => {
+                    self.create_conflict_record(mapping, "quantity_mismatch", inventory.quantity, erp_quantity).await?;
+                    return Ok(QuantityOutcome::Conflict);
+                }
+                crate::services::erp::erp_connection_service::ConflictResolution::LatestTimestamp => {
+                    inventory.quantity = erp_quantity;
+                }
+            }
+        }
+
+        self.inventory_repo.update_quantity(inventory.id, inventory.quantity).await
+            .map_err(|e| SyncError::SyncFailed(format!("Failed to update inventory: {}", e)))?;
+
+        self.update_mapping_sync_time(mapping.id).await?;
+
+        Ok(QuantityOutcome::Updated)
+    }
+
+    /// Write a batch of ERP quantities to the mapped Atlas inventory. Each chunk reads the
+    /// current quantities with one query, resolves them with `bulk_sync`, and writes the
+    /// inventory and mapping sync times with one UNNEST / ANY statement each.
+    async fn apply_erp_quantities(
+        &self,
+        connection: &ErpConnection,
+        changes: &[(&InventoryMapping, i32)],
+    ) -> Result<SyncResult> {
+        let mut result = SyncResult::default();
+
+        for chunk in changes.chunks(bulk_sync::UPSERT_CHUNK_SIZE) {
+            let planned = chunk
+                .iter()
+                .map(|(mapping, erp_quantity)| QuantityChange {
+                    mapping_id: mapping.id,
+                    inventory_id: mapping.atlas_inventory_id,
+                    erp_quantity: *erp_quantity,
+                })
+                .collect::<Vec<_>>();
+            let inventory_ids = planned.iter().map(|c| c.inventory_id).collect::<Vec<_>>();
+
+            let atlas_quantities: HashMap<Uuid, i32> = sqlx::query_as::<_, (Uuid, i32)>(
+                "SELECT id, quantity FROM inventory WHERE id = ANY($1)"
+            )
+            .bind(&inventory_ids)
+            .fetch_all(&self.db_pool)
+            .await?
+            .into_iter()
+            .collect();
+
+            let plan = bulk_sync::plan_quantity_changes(&planned, &atlas_quantities, &connection.conflict_resolution);
+            let (write_ids, write_quantities): (Vec<Uuid>, Vec<i32>) = plan.writes.iter().copied().unzip();
+            let synced_mappings = plan.synced_mappings(&planned);
+
+            let mut tx = self.db_pool.begin().await?;
+
+            if !write_ids.is_empty() {
+                sqlx::query(
+                    r#"
+                    UPDATE inventory AS i
+                    SET quantity = u.quantity, updated_at = NOW()
+                    FROM UNNEST($1::uuid[], $2::int4[]) AS u(id, quantity)
+                    WHERE i.id = u.id
+                    "#
+                )
+                .bind(&write_ids)
+                .bind(&write_quantities)
+                .execute(&mut *tx)
+                .await?;
+            }
+
+            if !synced_mappings.is_empty() {
+                sqlx::query(
+                    r#"
+                    UPDATE erp_inventory_mappings
+                    SET last_synced_at = NOW(), last_sync_status = 'success'
+                    WHERE id = ANY($1)
+                    "#
+                )
+                .bind(&synced_mappings)
+                .execute(&mut *tx)
+                .await?;
+            }
+
+            tx.commit().await?;
+
+            for ((mapping, erp_quantity), outcome) in chunk.iter().zip(&plan.outcomes) {
+                match outcome {
+                    PlannedOutcome::Write | PlannedOutcome::Unchanged => {
+                        result.items_synced += 1;
+                        result.items_updated += 1;
+                    }
+                    PlannedOutcome::Kept => result.items_skipped += 1,
+                    PlannedOutcome::Conflict { atlas_quantity } => {
+                        self.create_conflict_record(mapping, "quantity_mismatch", *atlas_quantity, *erp_quantity).await?;
+                        result.conflicts_detected += 1;
+                    }
+                    PlannedOutcome::Missing => {
+                        result.items_failed += 1;
+                        result.errors.push(SyncItemError {
+                            item_id: mapping.erp_item_id.clone(),
+                            error_message: format!("Inventory {} not found", mapping.atlas_inventory_id),
+                            error_type: "update_failed".to_string(),
+                        });
+                    }
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Write the stock of every mapped item to one CSV file in the outbound directory.
+    /// Nothing is uploaded when the file would be identical to the previous export.
+    async fn export_flat_file(&self, connection: &ErpConnection, triggered_by: &str) -> Result<SyncResult> {
+        let (sync_log_id, _in_flight) = self.create_sync_log(connection, "atlas_to_erp", triggered_by).await?;
+        let start_time = Utc::now();
+
+        let result = self.write_flat_file_export(connection, sync_log_id).await;
+
+        let duration = (Utc::now() - start_time).num_seconds() as i32;
+        self.complete_sync_log(sync_log_id, &result, duration).await?;
+
+        result
+    }
+
+    async fn write_flat_file_export(&self, connection: &ErpConnection, sync_log_id: Uuid) -> Result<SyncResult> {
+        let client = self.sftp_client(connection)?;
+        let mapping = connection.column_mapping.clone().unwrap_or_default();
+        let mut result = SyncResult::default();
+
+        let mut rows = Vec::new();
+        let mut exported = Vec::new();
+        for item in self.get_mappings_for_connection(connection.id).await? {
+            match self.inventory_repo.find_by_id(item.atlas_inventory_id).await {
+                Ok(Some(inventory)) => {
+                    rows.push(FlatFileRow {
+                        line: 0,
+                        item_id: item.erp_item_id.clone(),
+                        location_id: item.erp_location_id.clone(),
+                        item_name: None,
+                        quantity: inventory.quantity,
+                        lot_number: connection.sync_lot_batch.then(|| inventory.batch_number.clone()),
+                        expiry_date: connection.sync_lot_batch.then_some(inventory.expiry_date),
+                        ndc_code: None,
+                    });
+                    exported.push(item.id);
+                }
+                Ok(None) => result.items_skipped += 1,
+                Err(e) => {
+                    result.items_failed += 1;
+                    result.errors.push(SyncItemError {
+                        item_id: item.erp_item_id.clone(),
+                        error_message: e.to_string(),
+                        error_type: "fetch_failed".to_string(),
+                    });
+                }
+            }
+        }
+
+        let contents = write_csv(&rows, &mapping).map_err(SyncError::SyncFailed)?;
+        let checksum = checksum_sha256(&contents);
+
+        if self.last_outbound_checksum(connection.id).await?.as_deref() == Some(checksum.as_str()) {
+            tracing::info!("Stock unchanged since the last flat-file export for connection {}", connection.id);
+            result.items_skipped += rows.len() as i32;
+            return Ok(result);
+        }
+
+        let file_name = format!("atlas_inventory_{}.csv", Utc::now().format("%Y%m%dT%H%M%SZ"));
+        let size = contents.len();
+
+        if connection.sandbox_mode {
+            self.record_sandbox_write(
+                connection.id,
+                Some(sync_log_id),
+                None,
+                &file_name,
+                None,
+                "file_upload",
+                None,
+                serde_json::json!({
+                    "file_name": file_name,
+                    "rows": rows.len(),
+                    "size_bytes": size,
+                    "checksum": checksum,
+                    "content": String::from_utf8_lossy(&contents),
+                }),
+            ).await?;
+            result.items_synced += exported.len() as i32;
+            return Ok(result);
+        }
+
+        client.upload(&file_name, contents).await
+            .map_err(|e| SyncError::SftpError(e.to_string()))?;
+
+        self.record_transfer(connection.id, sync_log_id, "outbound", &file_name, size, Some(&checksum), "processed", rows.len() as i32, 0, None).await?;
+
+        for mapping_id in exported {
+            self.update_mapping_sync_time(mapping_id).await?;
+            result.items_synced += 1;
+            result.items_updated += 1;
+        }
+
+        Ok(result)
+    }
+
+    fn sftp_client(&self, connection: &ErpConnection) -> Result<SftpClient> {
+        let config = connection.flat_file_config.as_ref()
+            .ok_or_else(|| SyncError::SyncFailed("SFTP config not available".to_string()))?;
+
+        SftpClient::new(config.clone())
+            .map_err(|e| SyncError::SftpError(e.to_string()))
+    }
+
+    async fn record_file_error(
+        &self,
+        connection: &ErpConnection,
+        sync_log_id: Uuid,
+        file: &RemoteFile,
+        checksum: Option<&str>,
+        result: &mut SyncResult,
+        message: String,
+    ) -> Result<()> {
+        tracing::warn!("Flat file {} failed for connection {}: {}", file.name, connection.id, message);
+
+        self.record_transfer(connection.id, sync_log_id, "inbound", &file.name, file.size as usize, checksum, "failed", 0, 0, Some(&message)).await?;
+
+        result.items_failed += 1;
+        result.errors.push(SyncItemError {
+            item_id: file.name.clone(),
+            error_message: message,
+            error_type: "file_failed".to_string(),
+        });
+
+        Ok(())
+    }
+
+    /// Name of the file this content was already imported as, if any
+    async fn find_processed_inbound_file(&self, connection_id: Uuid, checksum: &str) -> Result<Option<String>> {
+        let file_name = sqlx::query_scalar::<_, String>(
+            r#"
+            SELECT file_name
+            FROM erp_flat_file_transfers
+            WHERE erp_connection_id = $1 AND direction = 'inbound'
+              AND status = 'processed' AND checksum_sha256 = $2
+            "#
+        )
+        .bind(connection_id)
+        .bind(checksum)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        Ok(file_name)
+    }
+
+    async fn last_outbound_checksum(&self, connection_id: Uuid) -> Result<Option<String>> {
+        let checksum = sqlx::query_scalar::<_, String>(
+            r#"
+            SELECT checksum_sha256
+            FROM erp_flat_file_transfers
+            WHERE erp_connection_id = $1 AND direction = 'outbound' AND status = 'processed'
+            ORDER BY created_at DESC
+            LIMIT 1
+            "#
+        )
+        .bind(connection_id)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        Ok(checksum)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn record_transfer(
+        &self,
+        connection_id: Uuid,
+        sync_log_id: Uuid,
+        direction: &str,
+        file_name: &str,
+        file_size: usize,
+        checksum: Option<&str>,
+        status: &str,
+        rows_total: i32,
+        rows_failed: i32,
+        error_message: Option<&str>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO erp_flat_file_transfers (
+                erp_connection_id, sync_log_id, direction, file_name, file_size,
+                checksum_sha256, status, rows_total, rows_failed, error_message
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            ON CONFLICT DO NOTHING
+            "#
+        )
+        .bind(connection_id)
+        .bind(sync_log_id)
+        .bind(direction)
+        .bind(file_name)
+        .bind(file_size as i64)
+        .bind(checksum)
+        .bind(status)
+        .bind(rows_total)
+        .bind(rows_failed)
+        .bind(error_message)
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    // ========================================================================
+    // Helper Methods
+    // ========================================================================
+
+    async fn sync_single_item_to_erp(
+        &self,
+        connection: &ErpConnection,
+        inventory: &Inventory,
+        fields: &ErpFieldMapping,
+        sync_log_id: Uuid,
+    ) -> Result<()> {
+        let mapping = self.get_or_create_mapping(connection, inventory).await?;
+
+        if !mapping.sync_enabled {
+            return Ok(());
+        }
+
+        match connection.erp_type {
+            ErpType::NetSuite => self.sync_to_netsuite(connection, inventory, &mapping, fields, Some(sync_log_id)).await,
+            ErpType::SapS4Hana => self.sync_to_sap(connection, inventory, &mapping, fields, Some(sync_log_id)).await,
+            ErpType::FlatFile => Ok(()),
+        }
+    }
+
+    async fn get_or_create_mapping(
+        &self,
         connection: &ErpConnection,
         inventory: &Inventory,
     ) -> Result<InventoryMapping> {
@@ -577,7 +2110,12 @@ impl ErpSyncService {
         // and match by NDC code or other identifiers
         let erp_item_id = format!("ATLAS_{}", inventory.pharmaceutical_id);
 
-        self.create_mapping(connection.id, inventory.id, &erp_item_id, None).await
+        // The subsidiary/plant serving the inventory's storage location, if any
+        let entity = connection.entity_for_storage_location(inventory.storage_location.as_deref());
+        let location_id = entity.and_then(|e| e.default_location_id.clone());
+        let entity_id = entity.map(|e| e.entity_id.clone());
+
+        self.create_mapping(connection.id, inventory.id, &erp_item_id, location_id, entity_id).await
     }
 
     async fn get_mapping_by_inventory(
@@ -587,7 +2125,7 @@ impl ErpSyncService {
     ) -> Result<Option<InventoryMapping>> {
         let row = sqlx::query!(
             r#"
-            SELECT id, erp_connection_id, atlas_inventory_id, erp_item_id, erp_location_id, sync_enabled
+            SELECT id, erp_connection_id, atlas_inventory_id, erp_item_id, erp_location_id, erp_entity_id, sync_enabled
             FROM erp_inventory_mappings
             WHERE erp_connection_id = $1 AND atlas_inventory_id = $2
             "#,
@@ -603,6 +2141,7 @@ impl ErpSyncService {
             atlas_inventory_id: r.atlas_inventory_id,
             erp_item_id: r.erp_item_id,
             erp_location_id: r.erp_location_id,
+            erp_entity_id: r.erp_entity_id,
             sync_enabled: r.sync_enabled,
         }))
     }
@@ -613,20 +2152,22 @@ impl ErpSyncService {
         inventory_id: Uuid,
         erp_item_id: &str,
         location_id: Option<String>,
+        entity_id: Option<String>,
     ) -> Result<InventoryMapping> {
         let id = Uuid::new_v4();
 
         sqlx::query!(
             r#"
             INSERT INTO erp_inventory_mappings (
-                id, erp_connection_id, atlas_inventory_id, erp_item_id, erp_location_id, sync_enabled
-            ) VALUES ($1, $2, $3, $4, $5, $6)
+                id, erp_connection_id, atlas_inventory_id, erp_item_id, erp_location_id, erp_entity_id, sync_enabled
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7)
             "#,
             id,
             connection_id,
             inventory_id,
             erp_item_id,
             location_id,
+            entity_id,
             true
         )
         .execute(&self.db_pool)
@@ -638,16 +2179,59 @@ impl ErpSyncService {
             atlas_inventory_id: inventory_id,
             erp_item_id: erp_item_id.to_string(),
             erp_location_id: location_id,
+            erp_entity_id: entity_id,
             sync_enabled: true,
         })
     }
 
+    async fn get_mapping_by_erp_item(
+        &self,
+        connection_id: Uuid,
+        erp_item_id: &str,
+        location_id: Option<&str>,
+    ) -> Result<Option<InventoryMapping>> {
+        use sqlx::Row;
+
+        let row = sqlx::query(
+            r#"
+            SELECT id, erp_connection_id, atlas_inventory_id, erp_item_id, erp_location_id, erp_entity_id, sync_enabled
+            FROM erp_inventory_mappings
+            WHERE erp_connection_id = $1 AND erp_item_id = $2
+              AND erp_location_id IS NOT DISTINCT FROM $3
+              AND sync_direction IN ('erp_to_atlas', 'bidirectional')
+            "#
+        )
+        .bind(connection_id)
+        .bind(erp_item_id)
+        .bind(location_id)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        Ok(row.map(|r| InventoryMapping {
+            id: r.get("id"),
+            erp_connection_id: r.get("erp_connection_id"),
+            atlas_inventory_id: r.get("atlas_inventory_id"),
+            erp_item_id: r.get("erp_item_id"),
+            erp_location_id: r.get("erp_location_id"),
+            erp_entity_id: r.get("erp_entity_id"),
+            sync_enabled: r.get("sync_enabled"),
+        }))
+    }
+
+    /// Enabled mappings, leaving out those of subsidiaries/plants with sync turned off
     async fn get_mappings_for_connection(&self, connection_id: Uuid) -> Result<Vec<InventoryMapping>> {
         let rows = sqlx::query!(
             r#"
-            SELECT id, erp_connection_id, atlas_inventory_id, erp_item_id, erp_location_id, sync_enabled
-            FROM erp_inventory_mappings
-            WHERE erp_connection_id = $1 AND sync_enabled = true
+            SELECT m.id, m.erp_connection_id, m.atlas_inventory_id, m.erp_item_id, m.erp_location_id,
+                   m.erp_entity_id, m.sync_enabled
+            FROM erp_inventory_mappings m
+            WHERE m.erp_connection_id = $1 AND m.sync_enabled = true
+              AND NOT EXISTS (
+                  SELECT 1 FROM erp_connection_entities e
+                  WHERE e.erp_connection_id = m.erp_connection_id
+                    AND e.entity_id = m.erp_entity_id
+                    AND e.sync_enabled = false
+              )
             "#,
             connection_id
         )
@@ -660,6 +2244,7 @@ impl ErpSyncService {
             atlas_inventory_id: r.atlas_inventory_id,
             erp_item_id: r.erp_item_id,
             erp_location_id: r.erp_location_id,
+            erp_entity_id: r.erp_entity_id,
             sync_enabled: r.sync_enabled,
         }).collect())
     }
@@ -679,31 +2264,39 @@ impl ErpSyncService {
         Ok(())
     }
 
+    /// The log is tracked for the shutdown drain while the returned guard is alive
     async fn create_sync_log(
         &self,
         connection: &ErpConnection,
         direction: &str,
         triggered_by: &str,
-    ) -> Result<Uuid> {
+    ) -> Result<(Uuid, InFlightGuard)> {
         let id = Uuid::new_v4();
+        let sync_type = match triggered_by {
+            "scheduler" => "full_sync",
+            "webhook" => "real_time",
+            _ => "manual",
+        };
 
         sqlx::query!(
             r#"
             INSERT INTO erp_sync_logs (
-                id, erp_connection_id, sync_type, sync_direction, triggered_by, status
-            ) VALUES ($1, $2, $3, $4, $5, $6)
+                id, erp_connection_id, sync_type, sync_direction, triggered_by, status, sandbox, entity_ids
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
             "#,
             id,
             connection.id,
-            "manual",
+            sync_type,
             direction,
             triggered_by,
-            "running"
+            "running",
+            connection.sandbox_mode,
+            connection.sync_entity_ids().as_deref()
         )
         .execute(&self.db_pool)
         .await?;
 
-        Ok(id)
+        Ok((id, Shutdown::track_sync(SyncLogTable::Erp, id)))
     }
 
     async fn complete_sync_log(
@@ -736,15 +2329,17 @@ impl ErpSyncService {
                 .await?;
             }
             Err(e) => {
+                // Runs failing while the instance shuts down were most likely stopped by it
                 sqlx::query!(
                     r#"
                     UPDATE erp_sync_logs
-                    SET status = 'failed', error_message = $2, completed_at = NOW(), duration_seconds = $3
+                    SET status = $4, error_message = $2, completed_at = NOW(), duration_seconds = $3
                     WHERE id = $1
                     "#,
                     log_id,
                     e.to_string(),
-                    duration
+                    duration,
+                    if Shutdown::requested() { "interrupted" } else { "failed" }
                 )
                 .execute(&self.db_pool)
                 .await?;
@@ -754,24 +2349,176 @@ impl ErpSyncService {
         Ok(())
     }
 
-    async fn create_conflict_record(&self, mapping: &InventoryMapping, conflict_type: &str) -> Result<()> {
-        sqlx::query!(
+    /// Queue a conflict for review; an open conflict of the mapping is refreshed with the latest values
+    async fn create_conflict_record(
+        &self,
+        mapping: &InventoryMapping,
+        conflict_type: &str,
+        atlas_quantity: i32,
+        erp_quantity: i32,
+    ) -> Result<()> {
+        sqlx::query(
             r#"
             INSERT INTO erp_conflict_queue (
                 id, erp_connection_id, erp_mapping_id, conflict_type, atlas_value, erp_value, status
-            ) VALUES ($1, $2, $3, $4, $5, $6, $7)
-            "#,
-            Uuid::new_v4(),
-            mapping.erp_connection_id,
-            mapping.id,
-            conflict_type,
-            serde_json::json!({}),
-            serde_json::json!({}),
-            "pending"
+            ) VALUES ($1, $2, $3, $4, $5, $6, 'pending')
+            ON CONFLICT (erp_mapping_id, conflict_type) WHERE status = 'pending'
+            DO UPDATE SET atlas_value = EXCLUDED.atlas_value, erp_value = EXCLUDED.erp_value, detected_at = NOW()
+            "#
         )
+        .bind(Uuid::new_v4())
+        .bind(mapping.erp_connection_id)
+        .bind(mapping.id)
+        .bind(conflict_type)
+        .bind(serde_json::json!({ "quantity": atlas_quantity }))
+        .bind(serde_json::json!({ "quantity": erp_quantity }))
         .execute(&self.db_pool)
         .await?;
 
         Ok(())
     }
 }
+
+// ============================================================================
+// ERP Record Helpers
+// ============================================================================
+
+/// On-hand quantity of a NetSuite item, from its first location when it has any
+fn netsuite_quantity(item: &NetSuiteInventoryItem) -> i32 {
+    item.locations.as_ref()
+        .and_then(|locations| locations.items.first())
+        .map(|location| location.quantity_on_hand)
+        .unwrap_or(item.quantity_on_hand)
+        .unwrap_or(0.0) as i32
+}
+
+/// SAP returns stock quantities as decimal strings
+fn sap_quantity(record: &MaterialStock) -> i32 {
+    record.stock_quantity.parse::<i32>().unwrap_or(0)
+}
+
+/// Open a paged stock read and fetch its first page, so a failed read can fall back
+/// before anything was applied
+async fn first_stock_page(
+    pages: std::result::Result<MaterialStockPages<'_>, SapError>,
+) -> std::result::Result<(MaterialStockPages<'_>, Vec<MaterialStock>), SapError> {
+    let mut pages = pages?;
+    let first = pages.next_page().await?.unwrap_or_default();
+    Ok((pages, first))
+}
+
+// ============================================================================
+// Sync Scheduler
+// ============================================================================
+
+/// Unix time of this process's last ERP scheduler tick (0 before the first one)
+static SCHEDULER_LAST_TICK: AtomicI64 = AtomicI64::new(0);
+/// Poll interval of this process's ERP scheduler, in minutes
+static SCHEDULER_POLL_MINUTES: AtomicU64 = AtomicU64::new(0);
+
+/// Liveness of the ERP sync scheduler in this process, for health checks
+#[derive(Debug, Clone, Copy)]
+pub struct SchedulerHeartbeat {
+    pub last_tick_at: DateTime<Utc>,
+    pub poll_minutes: u64,
+}
+
+/// Runs every connection whose `sync_frequency_minutes` has elapsed, in its default
+/// direction. Connections run one sync at a time; at most `erp_sync.max_concurrent`
+/// connections sync in parallel, and failing connections back off (see
+/// `ErpConnectionService::finish_sync`). The poll interval and concurrency are runtime
+/// settings, picked up on the next tick when changed.
+pub struct ErpSyncScheduler {
+    pool: PgPool,
+}
+
+impl ErpSyncScheduler {
+    /// Due connections picked up per tick
+    const BATCH_SIZE: i64 = 100;
+
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Last tick of the scheduler running in this process; None before it has started
+    pub fn heartbeat() -> Option<SchedulerHeartbeat> {
+        let last_tick = SCHEDULER_LAST_TICK.load(Ordering::Relaxed);
+        if last_tick == 0 {
+            return None;
+        }
+        Some(SchedulerHeartbeat {
+            last_tick_at: DateTime::from_timestamp(last_tick, 0)?,
+            poll_minutes: SCHEDULER_POLL_MINUTES.load(Ordering::Relaxed),
+        })
+    }
+
+    fn beat() {
+        SCHEDULER_LAST_TICK.store(Utc::now().timestamp(), Ordering::Relaxed);
+    }
+
+    /// Run the scheduler loop
+    pub async fn run(&self) {
+        let mut poll_minutes = RuntimeSettings::erp_sync_poll_minutes();
+        let mut ticker = tokio::time::interval(std::time::Duration::from_secs(poll_minutes * 60));
+
+        tracing::info!(
+            "ERP sync scheduler started - checking for due connections every {} minutes ({} concurrent syncs)",
+            poll_minutes, RuntimeSettings::erp_sync_max_concurrent()
+        );
+
+        SCHEDULER_POLL_MINUTES.store(poll_minutes, Ordering::Relaxed);
+
+        loop {
+            let current = RuntimeSettings::erp_sync_poll_minutes();
+            if current != poll_minutes {
+                tracing::info!("ERP sync scheduler now checking every {} minutes (was {})", current, poll_minutes);
+                poll_minutes = current;
+                let period = std::time::Duration::from_secs(poll_minutes * 60);
+                ticker = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+                SCHEDULER_POLL_MINUTES.store(poll_minutes, Ordering::Relaxed);
+            }
+
+            let deadline = ticker.tick().await;
+            crate::middleware::metrics::record_scheduler_lag("erp_sync", deadline);
+            Self::beat();
+            if crate::services::MaintenanceService::pauses("erp_sync")
+                || !crate::services::SchedulerLeader::leads("erp_sync").await
+            {
+                continue;
+            }
+            self.run_due_syncs().await;
+            Self::beat();
+        }
+    }
+
+    /// Sync every due connection, `erp_sync.max_concurrent` at a time
+    pub async fn run_due_syncs(&self) {
+        use futures::StreamExt;
+
+        let connection_service = ErpConnectionService::new(self.pool.clone());
+        let due = match connection_service.get_due_connections(Self::BATCH_SIZE).await {
+            Ok(due) => due,
+            Err(e) => {
+                tracing::error!("Failed to load due ERP connections: {}", e);
+                return;
+            }
+        };
+
+        let sync_service = ErpSyncService::new(self.pool.clone());
+        futures::stream::iter(due)
+            .for_each_concurrent(RuntimeSettings::erp_sync_max_concurrent(), |connection_id| {
+                let sync_service = &sync_service;
+                async move {
+                    match sync_service.run_scheduled_sync(connection_id).await {
+                        Ok(Some(result)) => tracing::info!(
+                            "Scheduled sync for connection {} complete: {} synced, {} skipped, {} failed",
+                            connection_id, result.items_synced, result.items_skipped, result.items_failed
+                        ),
+                        Ok(None) => tracing::debug!("Connection {} is already syncing", connection_id),
+                        Err(e) => tracing::error!("Scheduled sync for connection {} failed: {}", connection_id, e),
+                    }
+                }
+            })
+            .await;
+    }
+}
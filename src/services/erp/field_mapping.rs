@@ -0,0 +1,145 @@
+// ERP Field Mapping
+// Which ERP field holds each pharmaceutical attribute (NDC, lot, expiry), as stored in
+// erp_field_mapping_templates. Pure functions: templates are loaded by ErpFieldMappingService
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+use std::collections::HashMap;
+
+/// ERP field names of the attributes Atlas exchanges beyond stock levels.
+/// Template JSON may hold other keys (e.g. "quantity"); those are ignored here.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ErpFieldMapping {
+    #[serde(default)]
+    pub ndc_code: Option<String>,
+    #[serde(default)]
+    pub lot_number: Option<String>,
+    #[serde(default)]
+    pub expiry_date: Option<String>,
+}
+
+impl ErpFieldMapping {
+    /// Field names must be plain ERP field identifiers, e.g. `custitem_ndc_code` or `YY1_NDCCode_MDI`
+    pub fn validate(&self) -> Result<(), String> {
+        for (attribute, field) in self.fields() {
+            let Some(field) = field else { continue };
+
+            let valid = field.len() <= 100
+                && field.chars().next().map(|c| c.is_ascii_alphabetic() || c == '_').unwrap_or(false)
+                && field.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+            if !valid {
+                return Err(format!("Invalid ERP field name for {}: '{}'", attribute, field));
+            }
+        }
+        Ok(())
+    }
+
+    /// Attributes a sync needs but the mapping leaves empty. NDC identifies items on
+    /// every connection; lot and expiry are needed when the connection syncs lot/batch data.
+    pub fn missing_for_sync(&self, sync_lot_batch: bool) -> Vec<&'static str> {
+        self.fields()
+            .into_iter()
+            .filter(|(attribute, field)| {
+                let required = *attribute == "ndc_code" || sync_lot_batch;
+                required && field.map(|f| f.trim().is_empty()).unwrap_or(true)
+            })
+            .map(|(attribute, _)| attribute)
+            .collect()
+    }
+
+    /// Lot and expiry keyed by their ERP field names, for writing to the ERP
+    pub fn lot_fields(&self, lot_number: &str, expiry_date: &str) -> HashMap<String, String> {
+        let mut fields = HashMap::new();
+        if let Some(field) = &self.lot_number {
+            fields.insert(field.clone(), lot_number.to_string());
+        }
+        if let Some(field) = &self.expiry_date {
+            fields.insert(field.clone(), expiry_date.to_string());
+        }
+        fields
+    }
+
+    fn fields(&self) -> [(&'static str, Option<&str>); 3] {
+        [
+            ("ndc_code", self.ndc_code.as_deref()),
+            ("lot_number", self.lot_number.as_deref()),
+            ("expiry_date", self.expiry_date.as_deref()),
+        ]
+    }
+}
+
+/// Read a mapped field from an ERP record
+pub fn read_field(record: &Value, field: Option<&str>) -> Option<String> {
+    record.get(field?).and_then(field_value)
+}
+
+/// A field value as text; strings and numbers are accepted, blanks are not
+pub fn field_value(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) if !s.trim().is_empty() => Some(s.trim().to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn netsuite() -> ErpFieldMapping {
+        ErpFieldMapping {
+            ndc_code: Some("custitem_ndc_code".to_string()),
+            lot_number: Some("custitem_lot_number".to_string()),
+            expiry_date: None,
+        }
+    }
+
+    #[test]
+    fn test_validate_field_names() {
+        assert!(netsuite().validate().is_ok());
+
+        let mut mapping = netsuite();
+        mapping.expiry_date = Some("locations.items[0]".to_string());
+        assert!(mapping.validate().is_err());
+
+        mapping.expiry_date = Some("1st_field".to_string());
+        assert!(mapping.validate().is_err());
+    }
+
+    #[test]
+    fn test_missing_for_sync() {
+        assert!(netsuite().missing_for_sync(false).is_empty());
+        assert_eq!(netsuite().missing_for_sync(true), vec!["expiry_date"]);
+        assert_eq!(
+            ErpFieldMapping::default().missing_for_sync(true),
+            vec!["ndc_code", "lot_number", "expiry_date"]
+        );
+    }
+
+    #[test]
+    fn test_template_json_and_fields() {
+        // Stored templates carry extra keys
+        let mapping: ErpFieldMapping = serde_json::from_value(json!({
+            "quantity": "quantityOnHand",
+            "ndc_code": "custitem_ndc_code",
+            "lot_number": "custitem_lot_number"
+        }))
+        .unwrap();
+        assert_eq!(mapping, netsuite());
+
+        let record = json!({ "custitem_ndc_code": "0093-4155-73", "custitem_lot_number": "" });
+        assert_eq!(read_field(&record, mapping.ndc_code.as_deref()).as_deref(), Some("0093-4155-73"));
+        assert_eq!(read_field(&record, mapping.lot_number.as_deref()), None);
+        assert_eq!(read_field(&record, None), None);
+
+        let fields = mapping.lot_fields("B1", "2026-12-31");
+        assert_eq!(fields.get("custitem_lot_number").map(String::as_str), Some("B1"));
+        assert_eq!(fields.len(), 1);
+    }
+}
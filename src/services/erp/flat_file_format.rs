@@ -0,0 +1,407 @@
+// Flat-File Format
+// CSV column mapping, parsing and writing for flat-file ERP connections
+// Pure functions: the SFTP transport and database live elsewhere
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use sha2::{Digest, Sha256};
+
+// ============================================================================
+// Column Mapping
+// ============================================================================
+
+/// Which CSV column holds each Atlas field. Column names are matched against the
+/// header row case-insensitively; optional fields are ignored when unmapped.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct FlatFileColumnMapping {
+    pub item_id: String,
+    pub quantity: String,
+    #[serde(default)]
+    pub location_id: Option<String>,
+    #[serde(default)]
+    pub item_name: Option<String>,
+    #[serde(default)]
+    pub lot_number: Option<String>,
+    #[serde(default)]
+    pub expiry_date: Option<String>,
+    #[serde(default)]
+    pub ndc_code: Option<String>,
+    #[serde(default = "default_delimiter")]
+    pub delimiter: char,
+    /// chrono format of expiry dates, e.g. "%d/%m/%Y" (default: ISO 8601)
+    #[serde(default)]
+    pub date_format: Option<String>,
+}
+
+fn default_delimiter() -> char {
+    ','
+}
+
+impl Default for FlatFileColumnMapping {
+    fn default() -> Self {
+        Self {
+            item_id: "item_id".to_string(),
+            quantity: "quantity".to_string(),
+            location_id: Some("location_id".to_string()),
+            item_name: Some("item_name".to_string()),
+            lot_number: Some("lot_number".to_string()),
+            expiry_date: Some("expiry_date".to_string()),
+            ndc_code: Some("ndc_code".to_string()),
+            delimiter: default_delimiter(),
+            date_format: None,
+        }
+    }
+}
+
+/// An Atlas field a CSV column can be mapped to, described for mapping UIs
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct FlatFileField {
+    #[schema(value_type = String)]
+    pub field: &'static str,
+    #[schema(value_type = String)]
+    pub label: &'static str,
+    pub required: bool,
+    #[schema(value_type = String)]
+    pub description: &'static str,
+}
+
+pub const FLAT_FILE_FIELDS: &[FlatFileField] = &[
+    FlatFileField { field: "item_id", label: "ERP item ID", required: true, description: "Item or material number, matched against the connection's inventory mappings" },
+    FlatFileField { field: "quantity", label: "Quantity on hand", required: true, description: "Whole, non-negative stock quantity" },
+    FlatFileField { field: "location_id", label: "Location", required: false, description: "Warehouse or storage location; part of the mapping key when set" },
+    FlatFileField { field: "item_name", label: "Item name", required: false, description: "Informational only" },
+    FlatFileField { field: "lot_number", label: "Lot / batch number", required: false, description: "Written on export" },
+    FlatFileField { field: "expiry_date", label: "Expiry date", required: false, description: "Parsed with date_format (ISO 8601 by default)" },
+    FlatFileField { field: "ndc_code", label: "NDC code", required: false, description: "Informational only" },
+];
+
+impl FlatFileColumnMapping {
+    /// Mapped (field, column) pairs in file order
+    pub fn columns(&self) -> Vec<(&'static str, &str)> {
+        let mut columns = vec![("item_id", self.item_id.as_str())];
+        columns.extend(self.location_id.as_deref().map(|c| ("location_id", c)));
+        columns.extend(self.item_name.as_deref().map(|c| ("item_name", c)));
+        columns.push(("quantity", self.quantity.as_str()));
+        columns.extend(self.lot_number.as_deref().map(|c| ("lot_number", c)));
+        columns.extend(self.expiry_date.as_deref().map(|c| ("expiry_date", c)));
+        columns.extend(self.ndc_code.as_deref().map(|c| ("ndc_code", c)));
+        columns
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.item_id.trim().is_empty() || self.quantity.trim().is_empty() {
+            return Err("item_id and quantity columns are required".to_string());
+        }
+        if !self.delimiter.is_ascii() || self.delimiter.is_ascii_alphanumeric() || self.delimiter == '"' {
+            return Err(format!("Invalid delimiter '{}'", self.delimiter));
+        }
+
+        let columns = self.columns();
+        for (i, (field, column)) in columns.iter().enumerate() {
+            if column.trim().is_empty() {
+                return Err(format!("Column for {} is empty", field));
+            }
+            if let Some((other, _)) = columns[..i].iter().find(|(_, c)| c.trim().eq_ignore_ascii_case(column.trim())) {
+                return Err(format!("Column '{}' is mapped to both {} and {}", column, other, field));
+            }
+        }
+
+        if let Some(format) = &self.date_format {
+            let items = chrono::format::StrftimeItems::new(format);
+            if format.trim().is_empty() || items.into_iter().any(|item| matches!(item, chrono::format::Item::Error)) {
+                return Err(format!("Invalid date_format '{}'", format));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn parse_date(&self, value: &str) -> Option<NaiveDate> {
+        match &self.date_format {
+            Some(format) => NaiveDate::parse_from_str(value, format).ok(),
+            None => NaiveDate::parse_from_str(value, "%Y-%m-%d").ok(),
+        }
+    }
+
+    fn format_date(&self, date: NaiveDate) -> String {
+        date.format(self.date_format.as_deref().unwrap_or("%Y-%m-%d")).to_string()
+    }
+}
+
+// ============================================================================
+// Rows
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct FlatFileRow {
+    /// 1-based line in the file (0 for generated rows)
+    pub line: usize,
+    pub item_id: String,
+    pub location_id: Option<String>,
+    pub item_name: Option<String>,
+    pub quantity: i32,
+    pub lot_number: Option<String>,
+    pub expiry_date: Option<NaiveDate>,
+    pub ndc_code: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct FlatFileRowError {
+    pub line: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ParsedFlatFile {
+    pub headers: Vec<String>,
+    pub rows: Vec<FlatFileRow>,
+    pub errors: Vec<FlatFileRowError>,
+}
+
+/// Parse a CSV file with a header row. Missing mapped columns fail the whole file;
+/// bad rows are reported individually and skipped.
+pub fn parse_csv(contents: &[u8], mapping: &FlatFileColumnMapping) -> Result<ParsedFlatFile, String> {
+    let contents = contents.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(contents);
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(mapping.delimiter as u8)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(contents);
+
+    let headers: Vec<String> = reader
+        .headers()
+        .map_err(|e| format!("Could not read header row: {}", e))?
+        .iter()
+        .map(str::to_string)
+        .collect();
+
+    let index_of = |column: &str| headers.iter().position(|h| h.eq_ignore_ascii_case(column.trim()));
+    let mut indexes = std::collections::HashMap::new();
+    let mut missing = Vec::new();
+    for (field, column) in mapping.columns() {
+        match index_of(column) {
+            Some(index) => { indexes.insert(field, index); }
+            None => missing.push(column.to_string()),
+        }
+    }
+    if !missing.is_empty() {
+        return Err(format!("Missing columns: {}", missing.join(", ")));
+    }
+
+    let mut rows = Vec::new();
+    let mut errors = Vec::new();
+
+    for record in reader.records() {
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                let line = e.position().map(|p| p.line() as usize).unwrap_or(0);
+                errors.push(FlatFileRowError { line, message: e.to_string() });
+                continue;
+            }
+        };
+        let line = record.position().map(|p| p.line() as usize).unwrap_or(0);
+        if record.iter().all(str::is_empty) {
+            continue;
+        }
+
+        let value = |field: &str| {
+            indexes
+                .get(field)
+                .and_then(|&index| record.get(index))
+                .filter(|v| !v.is_empty())
+                .map(str::to_string)
+        };
+
+        let Some(item_id) = value("item_id") else {
+            errors.push(FlatFileRowError { line, message: "Missing item id".to_string() });
+            continue;
+        };
+
+        let quantity = match value("quantity").as_deref().map(parse_quantity) {
+            Some(Ok(quantity)) => quantity,
+            Some(Err(message)) => {
+                errors.push(FlatFileRowError { line, message });
+                continue;
+            }
+            None => {
+                errors.push(FlatFileRowError { line, message: "Missing quantity".to_string() });
+                continue;
+            }
+        };
+
+        let expiry_date = match value("expiry_date") {
+            Some(raw) => match mapping.parse_date(&raw) {
+                Some(date) => Some(date),
+                None => {
+                    errors.push(FlatFileRowError { line, message: format!("Invalid expiry date '{}'", raw) });
+                    continue;
+                }
+            },
+            None => None,
+        };
+
+        rows.push(FlatFileRow {
+            line,
+            item_id,
+            location_id: value("location_id"),
+            item_name: value("item_name"),
+            quantity,
+            lot_number: value("lot_number"),
+            expiry_date,
+            ndc_code: value("ndc_code"),
+        });
+    }
+
+    Ok(ParsedFlatFile { headers, rows, errors })
+}
+
+/// Whole, non-negative quantities; "120" and "120.00" are accepted
+fn parse_quantity(raw: &str) -> Result<i32, String> {
+    let value: f64 = raw
+        .replace(',', "")
+        .parse()
+        .map_err(|_| format!("Invalid quantity '{}'", raw))?;
+
+    if value < 0.0 || value.fract() != 0.0 || value > i32::MAX as f64 {
+        return Err(format!("Quantity must be a whole, non-negative number: '{}'", raw));
+    }
+    Ok(value as i32)
+}
+
+/// Write rows with the mapped columns as the header
+pub fn write_csv(rows: &[FlatFileRow], mapping: &FlatFileColumnMapping) -> Result<Vec<u8>, String> {
+    let columns = mapping.columns();
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(mapping.delimiter as u8)
+        .from_writer(Vec::new());
+
+    writer
+        .write_record(columns.iter().map(|(_, column)| *column))
+        .map_err(|e| e.to_string())?;
+
+    for row in rows {
+        let record: Vec<String> = columns
+            .iter()
+            .map(|(field, _)| match *field {
+                "item_id" => row.item_id.clone(),
+                "location_id" => row.location_id.clone().unwrap_or_default(),
+                "item_name" => row.item_name.clone().unwrap_or_default(),
+                "quantity" => row.quantity.to_string(),
+                "lot_number" => row.lot_number.clone().unwrap_or_default(),
+                "expiry_date" => row.expiry_date.map(|d| mapping.format_date(d)).unwrap_or_default(),
+                "ndc_code" => row.ndc_code.clone().unwrap_or_default(),
+                _ => String::new(),
+            })
+            .collect();
+        writer.write_record(&record).map_err(|e| e.to_string())?;
+    }
+
+    writer.into_inner().map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// Files
+// ============================================================================
+
+/// Hex SHA-256 of the file contents, used to detect re-delivered files
+pub fn checksum_sha256(contents: &[u8]) -> String {
+    hex::encode(Sha256::digest(contents))
+}
+
+/// Case-insensitive glob match supporting `*` and `?`
+pub fn matches_pattern(pattern: &str, name: &str) -> bool {
+    fn matches(pattern: &[char], name: &[char]) -> bool {
+        match pattern.split_first() {
+            None => name.is_empty(),
+            Some(('*', rest)) => (0..=name.len()).any(|i| matches(rest, &name[i..])),
+            Some(('?', rest)) => !name.is_empty() && matches(rest, &name[1..]),
+            Some((c, rest)) => name.first() == Some(c) && matches(rest, &name[1..]),
+        }
+    }
+
+    let pattern: Vec<char> = pattern.trim().to_lowercase().chars().collect();
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+    matches(&pattern, &name)
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mapping() -> FlatFileColumnMapping {
+        FlatFileColumnMapping {
+            item_id: "Material".to_string(),
+            quantity: "Qty".to_string(),
+            location_id: Some("Plant".to_string()),
+            item_name: None,
+            lot_number: Some("Batch".to_string()),
+            expiry_date: Some("Expiry".to_string()),
+            ndc_code: None,
+            delimiter: ';',
+            date_format: Some("%d/%m/%Y".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let file = "\u{feff}material;plant;qty;batch;expiry\n\
+                    M-100;1000;120.00;B1;31/12/2026\n\
+                    M-101;1000;-5;B2;31/12/2026\n\
+                    ;1000;3;B3;\n\
+                    M-102;2000;7;;2026-12-31\n\
+                    \n\
+                    M-103;2000;1,200;B4;";
+
+        let parsed = parse_csv(file.as_bytes(), &mapping()).unwrap();
+        assert_eq!(parsed.rows.len(), 2);
+        assert_eq!(parsed.rows[0].item_id, "M-100");
+        assert_eq!(parsed.rows[0].quantity, 120);
+        assert_eq!(parsed.rows[0].expiry_date, NaiveDate::from_ymd_opt(2026, 12, 31));
+        assert_eq!(parsed.rows[1].quantity, 1200);
+        assert_eq!(parsed.rows[1].line, 7);
+        assert_eq!(parsed.errors.iter().map(|e| e.line).collect::<Vec<_>>(), vec![3, 4, 5]);
+
+        assert!(parse_csv(b"material;qty\nM-1;1", &mapping()).is_err());
+    }
+
+    #[test]
+    fn test_write_csv_round_trip() {
+        let rows = vec![FlatFileRow {
+            line: 0,
+            item_id: "M-100".to_string(),
+            location_id: Some("1000".to_string()),
+            item_name: None,
+            quantity: 42,
+            lot_number: Some("B1".to_string()),
+            expiry_date: NaiveDate::from_ymd_opt(2027, 1, 15),
+            ndc_code: None,
+        }];
+
+        let written = write_csv(&rows, &mapping()).unwrap();
+        assert!(String::from_utf8_lossy(&written).starts_with("Material;Plant;Qty;Batch;Expiry\n"));
+
+        let parsed = parse_csv(&written, &mapping()).unwrap();
+        assert_eq!(parsed.rows, vec![FlatFileRow { line: 2, ..rows[0].clone() }]);
+    }
+
+    #[test]
+    fn test_mapping_validation() {
+        assert!(FlatFileColumnMapping::default().validate().is_ok());
+        assert!(FlatFileColumnMapping { lot_number: Some("ITEM_ID".to_string()), ..Default::default() }.validate().is_err());
+        assert!(FlatFileColumnMapping { delimiter: 'x', ..Default::default() }.validate().is_err());
+    }
+
+    #[test]
+    fn test_matches_pattern() {
+        assert!(matches_pattern("*.csv", "STOCK_20260101.CSV"));
+        assert!(matches_pattern("stock_????????.csv", "stock_20260101.csv"));
+        assert!(!matches_pattern("*.csv", "stock.csv.part"));
+        assert!(!matches_pattern("stock_*.csv", "orders_1.csv"));
+    }
+}
@@ -1,16 +1,35 @@
 // ERP Integration Module
-// Exports NetSuite and SAP clients, connection service, sync service, and AI assistant
+// Exports NetSuite, SAP and SFTP clients, connection service, sync service, field mapping templates,
+// webhook events, conflict review, API rate budgeting/circuit breaking, item catalog cache,
+// bulk sync planning and AI assistant
 
 pub mod netsuite_client;
 pub mod sap_client;
 pub mod erp_connection_service;
 pub mod erp_sync_service;
 pub mod erp_ai_assistant_service;
+pub mod sftp_client;
+pub mod flat_file_format;
+pub mod webhook_events;
+pub mod field_mapping;
+pub mod erp_field_mapping_service;
+pub mod erp_conflict_service;
+pub mod api_guard;
+pub mod erp_item_catalog_service;
+pub mod bulk_sync;
 
 pub use netsuite_client::{NetSuiteClient, NetSuiteConfig, NetSuiteError};
 pub use sap_client::{SapClient, SapConfig, SapEnvironment, SapError};
-pub use erp_connection_service::{ErpConnectionService, ErpConnection, ErpType, ConnectionStatus, ConflictResolution};
-pub use erp_sync_service::{ErpSyncService, SyncResult, SyncDirection};
+pub use sftp_client::{SftpClient, SftpConfig, SftpError};
+pub use flat_file_format::{FlatFileColumnMapping, FlatFileField, FLAT_FILE_FIELDS};
+pub use webhook_events::{ErpItemUpdate, WebhookEvent};
+pub use field_mapping::ErpFieldMapping;
+pub use erp_field_mapping_service::{ErpFieldMappingService, FieldMappingTemplate};
+pub use erp_conflict_service::{ErpConflictService, ConflictQueueItem};
+pub use api_guard::{CircuitState, CircuitStatus};
+pub use erp_item_catalog_service::{ErpItemCatalogService, ErpCatalogItem};
+pub use erp_connection_service::{ErpConnectionService, ErpConnection, ErpEntity, ErpEntityInput, ErpType, ConnectionStatus, ConflictResolution};
+pub use erp_sync_service::{ErpSyncService, ErpSyncScheduler, SchedulerHeartbeat, SyncResult, SyncDirection, OrderExport};
 pub use erp_ai_assistant_service::{
     ErpAiAssistantService,
     MappingSuggestion,
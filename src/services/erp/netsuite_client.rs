@@ -10,6 +10,10 @@ use hmac::{Hmac, Mac};
 use sha2::Sha256;
 use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
 use thiserror::Error;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+
+use crate::services::erp::api_guard::{self, ErpApiGuard};
 
 type HmacSha256 = Hmac<Sha256>;
 
@@ -42,6 +46,9 @@ pub enum NetSuiteError {
 
     #[error("Invalid configuration: {0}")]
     ConfigError(String),
+
+    #[error("NetSuite circuit open after repeated failures, retry in {0}s")]
+    CircuitOpen(u64),
 }
 
 pub type Result<T> = std::result::Result<T, NetSuiteError>;
@@ -58,6 +65,7 @@ pub struct NetSuiteConfig {
     pub token_id: String,
     pub token_secret: String,
     pub realm: Option<String>,  // Optional realm (defaults to account_id)
+    pub connection_id: Option<uuid::Uuid>,  // Rate budget / circuit breaker key (None = unguarded)
 }
 
 impl NetSuiteConfig {
@@ -97,18 +105,21 @@ pub struct NetSuiteInventoryItem {
     pub quantity_on_hand: Option<f64>,
     pub locations: Option<NetSuiteLocations>,
 
-    // Custom pharmaceutical fields (configured in NetSuite)
-    #[serde(rename = "custitem_ndc_code")]
-    pub ndc_code: Option<String>,
-    #[serde(rename = "custitem_lot_number")]
-    pub lot_number: Option<String>,
-    #[serde(rename = "custitem_expiry_date")]
-    pub expiry_date: Option<String>,
-
     // Standard fields
     pub cost: Option<f64>,
     pub manufacturer: Option<NetSuiteManufacturer>,
     pub description: Option<String>,
+
+    // Custom pharmaceutical fields (custitem_*), named by the connection's field mapping template
+    #[serde(flatten)]
+    pub custom_fields: serde_json::Map<String, serde_json::Value>,
+}
+
+impl NetSuiteInventoryItem {
+    /// A custom field named by the connection's field mapping (e.g. `custitem_ndc_code`)
+    pub fn custom_field(&self, field: Option<&str>) -> Option<String> {
+        self.custom_fields.get(field?).and_then(crate::services::erp::field_mapping::field_value)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -164,6 +175,7 @@ pub struct NetSuiteClient {
     config: NetSuiteConfig,
     base_url: String,
     http_client: Client,
+    guard: Option<Arc<ErpApiGuard>>,
 }
 
 impl NetSuiteClient {
@@ -181,10 +193,13 @@ impl NetSuiteClient {
             .build()
             .map_err(|e| NetSuiteError::NetworkError(e))?;
 
+        let guard = config.connection_id.map(api_guard::guard_for);
+
         Ok(Self {
             config,
             base_url,
             http_client,
+            guard,
         })
     }
 
@@ -224,6 +239,18 @@ impl NetSuiteClient {
         self.parse_response(response).await
     }
 
+    /// Items modified since the given time, one page at a time
+    pub fn inventory_modified_since(&self, since: DateTime<Utc>) -> InventoryPages<'_> {
+        // Minute precision in NetSuite's default date/time format; callers overlap the window
+        let q = format!("lastModifiedDate ON_OR_AFTER \"{}\"", since.format("%m/%d/%Y %I:%M %p"));
+        InventoryPages::new(self, q)
+    }
+
+    /// A batch of items by internal ID, one page at a time
+    pub fn inventory_items_by_id(&self, item_ids: &[String]) -> InventoryPages<'_> {
+        InventoryPages::new(self, internal_id_query(item_ids))
+    }
+
     /// Update inventory item quantity
     pub async fn update_inventory_quantity(
         &self,
@@ -279,16 +306,62 @@ impl NetSuiteClient {
     }
 
     // ========================================================================
-    // Purchase Order Operations
+    // Order Operations
     // ========================================================================
 
-    /// Create a purchase order
+    /// Create a sales order. Retries carrying the same idempotency key return the
+    /// original record instead of creating a duplicate.
+    pub async fn create_sales_order(
+        &self,
+        payload: &serde_json::Value,
+        idempotency_key: &str,
+    ) -> Result<String> {
+        self.create_transaction_record("salesOrder", payload, idempotency_key).await
+    }
+
+    /// Create a purchase order (idempotent, see `create_sales_order`)
     pub async fn create_purchase_order(
         &self,
         payload: &serde_json::Value,
+        idempotency_key: &str,
     ) -> Result<String> {
-        let url = format!("{}/purchaseOrder", self.base_url);
-        let response = self.post(&url, &[], payload).await?;
+        self.create_transaction_record("purchaseOrder", payload, idempotency_key).await
+    }
+
+    /// Document number (tranId) NetSuite assigned to a transaction record
+    pub async fn get_transaction_number(&self, record_type: &str, id: &str) -> Result<String> {
+        let url = format!("{}/{}/{}", self.base_url, record_type, id);
+        let params = vec![("fields", "tranId".to_string())];
+        let response = self.get(&url, &params).await?;
+
+        #[derive(Deserialize)]
+        struct TransactionNumber {
+            #[serde(rename = "tranId")]
+            tran_id: String,
+        }
+
+        let record: TransactionNumber = self.parse_response(response).await?;
+        Ok(record.tran_id)
+    }
+
+    async fn create_transaction_record(
+        &self,
+        record_type: &str,
+        payload: &serde_json::Value,
+        idempotency_key: &str,
+    ) -> Result<String> {
+        let url = format!("{}/{}", self.base_url, record_type);
+        let auth_header = self.generate_oauth_header("POST", &url, &[])?;
+
+        let request = self
+            .http_client
+            .post(&url)
+            .header("Authorization", auth_header)
+            .header("Content-Type", "application/json")
+            .header("X-NetSuite-Idempotency-Key", idempotency_key)
+            .json(payload);
+
+        let response = self.execute_with_retry(request).await?;
 
         #[derive(Deserialize)]
         struct CreateResponse {
@@ -467,9 +540,17 @@ impl NetSuiteClient {
         loop {
             attempts += 1;
 
+            if let Some(guard) = &self.guard {
+                guard.acquire().await
+                    .map_err(|retry_after| NetSuiteError::CircuitOpen(retry_after.as_secs()))?;
+            }
+
             match request.try_clone() {
                 Some(req) => match req.send().await {
                     Ok(response) => {
+                        if let Some(guard) = &self.guard {
+                            guard.record_status(response.status());
+                        }
                         if response.status() == StatusCode::TOO_MANY_REQUESTS && attempts < MAX_RETRIES {
                             // Rate limited - exponential backoff
                             let delay = std::time::Duration::from_secs(2u64.pow(attempts));
@@ -479,12 +560,20 @@ impl NetSuiteClient {
                         return Ok(response);
                     }
                     Err(e) if attempts < MAX_RETRIES => {
+                        if let Some(guard) = &self.guard {
+                            guard.record_no_response();
+                        }
                         // Network error - retry with exponential backoff
                         let delay = std::time::Duration::from_secs(2u64.pow(attempts));
                         tokio::time::sleep(delay).await;
                         continue;
                     }
-                    Err(e) => return Err(NetSuiteError::NetworkError(e)),
+                    Err(e) => {
+                        if let Some(guard) = &self.guard {
+                            guard.record_no_response();
+                        }
+                        return Err(NetSuiteError::NetworkError(e));
+                    }
                 },
                 None => {
                     return Err(NetSuiteError::AuthError(
@@ -541,6 +630,49 @@ impl NetSuiteClient {
     }
 }
 
+// ============================================================================
+// Inventory Pages
+// ============================================================================
+
+/// Inventory search read one page at a time, so callers apply each page as it arrives
+/// instead of holding the whole result
+pub struct InventoryPages<'a> {
+    client: &'a NetSuiteClient,
+    q: String,
+    offset: i32,
+    done: bool,
+}
+
+impl<'a> InventoryPages<'a> {
+    const PAGE_SIZE: i32 = 1000;
+
+    fn new(client: &'a NetSuiteClient, q: String) -> Self {
+        Self { client, q, offset: 0, done: false }
+    }
+
+    /// Next page of items, `None` once the search is exhausted
+    pub async fn next_page(&mut self) -> Result<Option<Vec<NetSuiteInventoryItem>>> {
+        if self.done {
+            return Ok(None);
+        }
+
+        let page = self
+            .client
+            .search_inventory(NetSuiteSearchParams {
+                q: Some(self.q.clone()),
+                limit: Some(Self::PAGE_SIZE),
+                offset: Some(self.offset),
+                fields: None,
+            })
+            .await?;
+
+        self.offset += page.count;
+        self.done = !page.has_more || page.count == 0;
+
+        Ok(Some(page.items))
+    }
+}
+
 // ============================================================================
 // Helper Functions
 // ============================================================================
@@ -549,6 +681,18 @@ fn percent_encode(input: &str) -> String {
     utf8_percent_encode(input, NON_ALPHANUMERIC).to_string()
 }
 
+/// `id ANY_OF [1, 2, 3]`; non-numeric IDs could not match and are dropped
+fn internal_id_query(item_ids: &[String]) -> String {
+    let ids = item_ids
+        .iter()
+        .filter(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()))
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    format!("id ANY_OF [{}]", ids)
+}
+
 // ============================================================================
 // Tests
 // ============================================================================
@@ -566,6 +710,7 @@ mod tests {
             token_id: "test".to_string(),
             token_secret: "test".to_string(),
             realm: None,
+            connection_id: None,
         };
 
         assert!(config.validate().is_err());
@@ -576,4 +721,12 @@ mod tests {
         assert_eq!(percent_encode("hello world"), "hello%20world");
         assert_eq!(percent_encode("test@example.com"), "test%40example%2Ecom");
     }
+
+    #[test]
+    fn test_internal_id_query() {
+        assert_eq!(
+            internal_id_query(&["101".to_string(), "x; drop".to_string(), "205".to_string()]),
+            "id ANY_OF [101, 205]"
+        );
+    }
 }
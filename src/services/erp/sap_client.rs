@@ -4,10 +4,13 @@
 
 use reqwest::{Client, Response, StatusCode};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 use chrono::{DateTime, Duration, Utc};
 use thiserror::Error;
 
+use crate::services::erp::api_guard::{self, ErpApiGuard};
+
 // ============================================================================
 // Error Types
 // ============================================================================
@@ -40,6 +43,12 @@ pub enum SapError {
 
     #[error("OData error: {0}")]
     ODataError(String),
+
+    #[error("Delta token expired or unknown")]
+    DeltaTokenExpired,
+
+    #[error("SAP circuit open after repeated failures, retry in {0}s")]
+    CircuitOpen(u64),
 }
 
 pub type Result<T> = std::result::Result<T, SapError>;
@@ -57,6 +66,7 @@ pub struct SapConfig {
     pub environment: SapEnvironment,
     pub plant: Option<String>,  // Default plant for inventory operations
     pub company_code: Option<String>,
+    pub connection_id: Option<uuid::Uuid>,  // Rate budget / circuit breaker key (None = unguarded)
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -102,6 +112,22 @@ pub struct ODataSingleResponse<T> {
     pub d: T,
 }
 
+// Change-tracked collection: paged via __next, the last page carries the __delta link
+#[derive(Debug, Deserialize)]
+pub struct ODataDeltaResponse<T> {
+    pub d: ODataDeltaData<T>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ODataDeltaData<T> {
+    pub results: Vec<T>,
+    #[serde(rename = "__next")]
+    pub next: Option<String>,
+    #[serde(rename = "__delta")]
+    pub delta: Option<String>,
+}
+
+
 // Material Stock
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "PascalCase")]
@@ -137,14 +163,11 @@ pub struct MaterialDocumentItem {
     pub goods_movement_type: String,  // "501" = receipt without PO, "101" = GR for PO
     pub quantity_in_entry_unit: String,
     pub entry_unit: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub batch: Option<String>,
-
-    // Custom pharmaceutical fields (requires configuration in SAP)
-    #[serde(rename = "YY1_ExpiryDate_MDI", skip_serializing_if = "Option::is_none")]
-    pub expiry_date: Option<String>,
-    #[serde(rename = "YY1_NDCCode_MDI", skip_serializing_if = "Option::is_none")]
-    pub ndc_code: Option<String>,
+
+    // Batch and custom pharmaceutical fields (e.g. YY1_ExpiryDate_MDI), named by the
+    // connection's field mapping template
+    #[serde(flatten)]
+    pub custom_fields: HashMap<String, String>,
 }
 
 // Product Master Data
@@ -175,6 +198,62 @@ pub struct MaterialDocumentItems {
     pub results: Vec<MaterialDocumentItem>,
 }
 
+// Sales Order (API_SALES_ORDER_SRV)
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SalesOrder {
+    pub sales_order_type: String,         // "OR" = standard order
+    pub sales_organization: String,
+    pub distribution_channel: String,
+    pub organization_division: String,
+    pub sold_to_party: String,
+    pub purchase_order_by_customer: String,  // External reference (Atlas transaction)
+    #[serde(rename = "to_Item")]
+    pub to_item: SalesOrderItems,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SalesOrderItems {
+    pub results: Vec<SalesOrderItem>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SalesOrderItem {
+    pub material: String,
+    pub requested_quantity: String,
+    pub requested_quantity_unit: String,
+}
+
+// Purchase Order (API_PURCHASEORDER_PROCESS_SRV)
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PurchaseOrder {
+    pub purchase_order_type: String,      // "NB" = standard PO
+    pub company_code: String,
+    pub purchasing_organization: String,
+    pub purchasing_group: String,
+    pub supplier: String,
+    pub document_currency: String,
+    #[serde(rename = "to_PurchaseOrderItem")]
+    pub to_purchase_order_item: PurchaseOrderItems,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PurchaseOrderItems {
+    pub results: Vec<PurchaseOrderItem>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PurchaseOrderItem {
+    pub material: String,
+    pub plant: String,
+    pub order_quantity: String,
+    pub purchase_order_quantity_unit: String,
+    pub net_price_amount: String,
+}
+
 // ============================================================================
 // Token Cache
 // ============================================================================
@@ -193,6 +272,7 @@ pub struct SapClient {
     config: SapConfig,
     http_client: Client,
     token_cache: Arc<RwLock<Option<TokenCache>>>,
+    guard: Option<Arc<ErpApiGuard>>,
 }
 
 impl SapClient {
@@ -206,10 +286,13 @@ impl SapClient {
             .build()
             .map_err(SapError::NetworkError)?;
 
+        let guard = config.connection_id.map(api_guard::guard_for);
+
         Ok(Self {
             config,
             http_client,
             token_cache: Arc::new(RwLock::new(None)),
+            guard,
         })
     }
 
@@ -236,14 +319,14 @@ impl SapClient {
             material_number, plant, storage_location
         );
 
-        let response = self
+        let request = self
             .http_client
             .get(&url)
             .bearer_auth(&token)
             .header("Accept", "application/json")
-            .query(&[("$filter", filter)])
-            .send()
-            .await?;
+            .query(&[("$filter", filter)]);
+
+        let response = self.send(request).await?;
 
         self.handle_odata_response::<MaterialStock>(response)
             .await?
@@ -266,18 +349,73 @@ impl SapClient {
 
         let filter = format!("Material eq '{}'", material_number);
 
-        let response = self
+        let request = self
             .http_client
             .get(&url)
             .bearer_auth(&token)
             .header("Accept", "application/json")
-            .query(&[("$filter", filter)])
-            .send()
-            .await?;
+            .query(&[("$filter", filter)]);
+
+        let response = self.send(request).await?;
 
         self.handle_odata_response::<MaterialStock>(response).await
     }
 
+    /// Change-tracked read of the plants' stock, one page at a time. Without a delta token
+    /// this returns all stock and starts tracking; with one, only the records changed since
+    /// it was issued. Pages fail with `DeltaTokenExpired` once SAP has discarded the token.
+    pub async fn material_stock_delta_pages(
+        &self,
+        plants: &[String],
+        delta_token: Option<&str>,
+    ) -> Result<MaterialStockPages<'_>> {
+        let access_token = self.get_access_token().await?;
+
+        let request = self
+            .http_client
+            .get(self.material_stock_url())
+            .bearer_auth(&access_token)
+            .header("Accept", "application/json");
+        let request = match delta_token {
+            Some(delta) => request.query(&[("!deltatoken", format!("'{}'", delta))]),
+            None => request
+                .header("Prefer", "odata.track-changes")
+                .query(&[("$filter", plant_filter(plants))]),
+        };
+
+        Ok(MaterialStockPages {
+            client: self,
+            access_token,
+            pending: Some(request),
+            tracks_delta: delta_token.is_some(),
+            delta_token: None,
+        })
+    }
+
+    /// Stock of a batch of materials in one plant, one page at a time
+    pub async fn material_stock_pages(&self, plant: &str, materials: &[String]) -> Result<MaterialStockPages<'_>> {
+        let access_token = self.get_access_token().await?;
+
+        let request = self
+            .http_client
+            .get(self.material_stock_url())
+            .bearer_auth(&access_token)
+            .header("Accept", "application/json")
+            .query(&[("$filter", material_filter(plant, materials))]);
+
+        Ok(MaterialStockPages {
+            client: self,
+            access_token,
+            pending: Some(request),
+            tracks_delta: false,
+            delta_token: None,
+        })
+    }
+
+    fn material_stock_url(&self) -> String {
+        format!("{}/sap/opu/odata/sap/API_MATERIAL_STOCK_SRV/MaterialStock", self.config.base_url)
+    }
+
     // ========================================================================
     // Goods Movement Operations
     // ========================================================================
@@ -287,23 +425,23 @@ impl SapClient {
         let token = self.get_access_token().await?;
 
         // Get CSRF token (required for POST/PATCH/DELETE)
-        let csrf_token = self.get_csrf_token(&token).await?;
+        let csrf_token = self.get_csrf_token(&token, "API_MATERIAL_DOCUMENT_SRV").await?;
 
         let url = format!(
             "{}/sap/opu/odata/sap/API_MATERIAL_DOCUMENT_SRV/A_MaterialDocumentHeader",
             self.config.base_url
         );
 
-        let response = self
+        let request = self
             .http_client
             .post(&url)
             .bearer_auth(&token)
             .header("X-CSRF-Token", csrf_token)
             .header("Content-Type", "application/json")
             .header("Accept", "application/json")
-            .json(&movement)
-            .send()
-            .await?;
+            .json(&movement);
+
+        let response = self.send(request).await?;
 
         let result: ODataSingleResponse<MaterialDocumentHeader> = self.parse_response(response).await?;
         Ok(result.d.material_document)
@@ -317,9 +455,7 @@ impl SapClient {
         storage_location: &str,
         quantity_delta: f64,
         unit: &str,
-        batch: Option<String>,
-        expiry_date: Option<String>,
-        ndc_code: Option<String>,
+        custom_fields: HashMap<String, String>,
     ) -> Result<String> {
         let item = MaterialDocumentItem {
             material: material.to_string(),
@@ -328,9 +464,7 @@ impl SapClient {
             goods_movement_type: "501".to_string(),
             quantity_in_entry_unit: quantity_delta.to_string(),
             entry_unit: unit.to_string(),
-            batch,
-            expiry_date,
-            ndc_code,
+            custom_fields,
         };
 
         let movement = GoodsMovement {
@@ -345,6 +479,69 @@ impl SapClient {
         self.post_goods_movement(movement).await
     }
 
+    // ========================================================================
+    // Order Operations
+    // ========================================================================
+
+    /// Create a sales order, returning the SAP document number. `request_id` is sent as
+    /// the Repeatability-Request-ID so a retried request is not booked twice.
+    pub async fn create_sales_order(&self, order: SalesOrder, request_id: &str) -> Result<String> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct CreatedSalesOrder {
+            sales_order: String,
+        }
+
+        let created: CreatedSalesOrder = self
+            .post_repeatable("API_SALES_ORDER_SRV", "A_SalesOrder", &order, request_id)
+            .await?;
+        Ok(created.sales_order)
+    }
+
+    /// Create a purchase order, returning the SAP document number (idempotent, see
+    /// `create_sales_order`)
+    pub async fn create_purchase_order(&self, order: PurchaseOrder, request_id: &str) -> Result<String> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct CreatedPurchaseOrder {
+            purchase_order: String,
+        }
+
+        let created: CreatedPurchaseOrder = self
+            .post_repeatable("API_PURCHASEORDER_PROCESS_SRV", "A_PurchaseOrder", &order, request_id)
+            .await?;
+        Ok(created.purchase_order)
+    }
+
+    async fn post_repeatable<B: Serialize, T: serde::de::DeserializeOwned>(
+        &self,
+        service: &str,
+        entity_set: &str,
+        body: &B,
+        request_id: &str,
+    ) -> Result<T> {
+        let token = self.get_access_token().await?;
+        let csrf_token = self.get_csrf_token(&token, service).await?;
+
+        let url = format!("{}/sap/opu/odata/sap/{}/{}", self.config.base_url, service, entity_set);
+
+        let request = self
+            .http_client
+            .post(&url)
+            .bearer_auth(&token)
+            .header("X-CSRF-Token", csrf_token)
+            .header("Content-Type", "application/json")
+            .header("Accept", "application/json")
+            .header("Repeatability-Request-ID", request_id)
+            .header("Repeatability-First-Sent", Utc::now().to_rfc2822())
+            .json(body);
+
+        let response = self.send(request).await?;
+
+        let result: ODataSingleResponse<T> = self.parse_response(response).await?;
+        Ok(result.d)
+    }
+
     // ========================================================================
     // Product Master Data
     // ========================================================================
@@ -358,13 +555,13 @@ impl SapClient {
             self.config.base_url, material_number
         );
 
-        let response = self
+        let request = self
             .http_client
             .get(&url)
             .bearer_auth(&token)
-            .header("Accept", "application/json")
-            .send()
-            .await?;
+            .header("Accept", "application/json");
+
+        let response = self.send(request).await?;
 
         let result: ODataSingleResponse<Product> = self.parse_response(response).await?;
         Ok(result.d)
@@ -381,14 +578,14 @@ impl SapClient {
 
         let filter = format!("contains(Product,'{}') or contains(ProductDescription,'{}')", search_term, search_term);
 
-        let response = self
+        let request = self
             .http_client
             .get(&url)
             .bearer_auth(&token)
             .header("Accept", "application/json")
-            .query(&[("$filter", filter), ("$top", "100".to_string())])
-            .send()
-            .await?;
+            .query(&[("$filter", filter), ("$top", "100".to_string())]);
+
+        let response = self.send(request).await?;
 
         self.handle_odata_response::<Product>(response).await
     }
@@ -425,14 +622,14 @@ impl SapClient {
     async fn request_new_token(&self) -> Result<TokenCache> {
         let credentials = base64::encode(format!("{}:{}", self.config.client_id, self.config.client_secret));
 
-        let response = self
+        let request = self
             .http_client
             .post(&self.config.token_endpoint)
             .header("Authorization", format!("Basic {}", credentials))
             .header("Content-Type", "application/x-www-form-urlencoded")
-            .body("grant_type=client_credentials")
-            .send()
-            .await?;
+            .body("grant_type=client_credentials");
+
+        let response = self.send(request).await?;
 
         if !response.status().is_success() {
             let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
@@ -459,19 +656,16 @@ impl SapClient {
     // CSRF Token Management (Required for Write Operations)
     // ========================================================================
 
-    async fn get_csrf_token(&self, access_token: &str) -> Result<String> {
-        let url = format!(
-            "{}/sap/opu/odata/sap/API_MATERIAL_DOCUMENT_SRV",
-            self.config.base_url
-        );
+    async fn get_csrf_token(&self, access_token: &str, service: &str) -> Result<String> {
+        let url = format!("{}/sap/opu/odata/sap/{}", self.config.base_url, service);
 
-        let response = self
+        let request = self
             .http_client
             .get(&url)
             .bearer_auth(access_token)
-            .header("X-CSRF-Token", "Fetch")
-            .send()
-            .await?;
+            .header("X-CSRF-Token", "Fetch");
+
+        let response = self.send(request).await?;
 
         response
             .headers()
@@ -485,6 +679,27 @@ impl SapClient {
     // Helper Methods
     // ========================================================================
 
+    /// Send a request within the connection's rate budget, feeding its circuit breaker
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<Response> {
+        let Some(guard) = &self.guard else {
+            return Ok(request.send().await?);
+        };
+
+        guard.acquire().await
+            .map_err(|retry_after| SapError::CircuitOpen(retry_after.as_secs()))?;
+
+        match request.send().await {
+            Ok(response) => {
+                guard.record_status(response.status());
+                Ok(response)
+            }
+            Err(e) => {
+                guard.record_no_response();
+                Err(SapError::NetworkError(e))
+            }
+        }
+    }
+
     async fn handle_odata_response<T: serde::de::DeserializeOwned>(
         &self,
         response: Response,
@@ -532,25 +747,134 @@ impl SapClient {
             self.config.base_url
         );
 
-        let response = self
+        let request = self
             .http_client
             .get(&url)
-            .bearer_auth(&token)
-            .send()
-            .await?;
+            .bearer_auth(&token);
+
+        let response = self.send(request).await?;
 
         Ok(response.status().is_success())
     }
 }
 
+// ============================================================================
+// Material Stock Pages
+// ============================================================================
+
+/// Paged MaterialStock read following `__next` links, so callers apply each page as it
+/// arrives instead of holding the whole result. The last page of a change-tracked read
+/// carries the delta token for the next read.
+pub struct MaterialStockPages<'a> {
+    client: &'a SapClient,
+    access_token: String,
+    pending: Option<reqwest::RequestBuilder>,
+    tracks_delta: bool,
+    delta_token: Option<String>,
+}
+
+impl MaterialStockPages<'_> {
+    /// Next page of stock records, `None` once the read is complete
+    pub async fn next_page(&mut self) -> Result<Option<Vec<MaterialStock>>> {
+        let Some(request) = self.pending.take() else {
+            return Ok(None);
+        };
+
+        let response = self.client.send(request).await?;
+        let status = response.status();
+        if status == StatusCode::GONE || (self.tracks_delta && status == StatusCode::NOT_FOUND) {
+            return Err(SapError::DeltaTokenExpired);
+        }
+
+        let page: ODataDeltaResponse<MaterialStock> = self.client.parse_response(response).await?;
+        match page.d.next {
+            Some(next) => {
+                self.pending = Some(
+                    self.client
+                        .http_client
+                        .get(&next)
+                        .bearer_auth(&self.access_token)
+                        .header("Accept", "application/json"),
+                );
+            }
+            None => self.delta_token = page.d.delta.as_deref().and_then(delta_token_from_link),
+        }
+
+        Ok(Some(page.d.results))
+    }
+
+    /// Token for the next change-tracked read, once the last page was read
+    pub fn delta_token(&self) -> Option<&str> {
+        self.delta_token.as_deref()
+    }
+}
+
 // ============================================================================
 // Tests
 // ============================================================================
 
+/// OData filter selecting the stock of any of the plants
+fn plant_filter(plants: &[String]) -> String {
+    plants
+        .iter()
+        .map(|plant| format!("Plant eq '{}'", plant))
+        .collect::<Vec<_>>()
+        .join(" or ")
+}
+
+/// `Plant eq '<plant>' and (Material eq '<a>' or Material eq '<b>' ...)`
+pub fn material_filter(plant: &str, materials: &[String]) -> String {
+    let materials = materials
+        .iter()
+        .map(|material| format!("Material eq '{}'", material.replace('\'', "''")))
+        .collect::<Vec<_>>()
+        .join(" or ");
+
+    format!("Plant eq '{}' and ({})", plant, materials)
+}
+
+/// Extract the token from an OData `__delta` link (`...?!deltatoken='<token>'`)
+fn delta_token_from_link(link: &str) -> Option<String> {
+    let start = link.find("!deltatoken=")? + "!deltatoken=".len();
+    let token = link[start..].split('&').next()?.trim_matches('\'');
+    let token = token.replace("%27", "");
+    (!token.is_empty()).then_some(token)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_delta_token_from_link() {
+        assert_eq!(
+            delta_token_from_link("https://sap/MaterialStock?!deltatoken='D20261016_00001'"),
+            Some("D20261016_00001".to_string())
+        );
+        assert_eq!(
+            delta_token_from_link("https://sap/MaterialStock?!deltatoken=%27abc%27&$format=json"),
+            Some("abc".to_string())
+        );
+        assert_eq!(delta_token_from_link("https://sap/MaterialStock"), None);
+    }
+
+    #[test]
+    fn test_plant_filter() {
+        assert_eq!(plant_filter(&["1000".to_string()]), "Plant eq '1000'");
+        assert_eq!(
+            plant_filter(&["1000".to_string(), "2000".to_string()]),
+            "Plant eq '1000' or Plant eq '2000'"
+        );
+    }
+
+    #[test]
+    fn test_material_filter() {
+        assert_eq!(
+            material_filter("1000", &["MAT-1".to_string(), "O'NEIL".to_string()]),
+            "Plant eq '1000' and (Material eq 'MAT-1' or Material eq 'O''NEIL')"
+        );
+    }
+
     #[test]
     fn test_config_validation() {
         let config = SapConfig {
@@ -561,6 +885,7 @@ mod tests {
             environment: SapEnvironment::Cloud,
             plant: None,
             company_code: None,
+            connection_id: None,
         };
 
         assert!(config.validate().is_err());
@@ -0,0 +1,306 @@
+// SFTP Client for flat-file ERP connections
+// Lists, downloads, uploads and archives CSV files on the ERP's SFTP server
+// libssh2 is blocking, so every operation runs on the blocking thread pool
+
+use ssh2::{HashType, RenameFlags, Session, Sftp};
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use thiserror::Error;
+
+/// Files larger than this are refused rather than loaded into memory
+pub const MAX_FILE_BYTES: u64 = 50 * 1024 * 1024;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+const SESSION_TIMEOUT_MS: u32 = 60_000;
+
+// ============================================================================
+// Error Types
+// ============================================================================
+
+#[derive(Error, Debug)]
+pub enum SftpError {
+    #[error("Connection failed: {0}")]
+    ConnectionError(String),
+
+    #[error("Host key mismatch: expected {expected}, server presented {actual}")]
+    HostKeyMismatch { expected: String, actual: String },
+
+    #[error("Authentication failed: {0}")]
+    AuthError(String),
+
+    #[error("SFTP error: {0}")]
+    Ssh(#[from] ssh2::Error),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("File too large: {0} ({1} bytes)")]
+    FileTooLarge(String, u64),
+
+    #[error("Invalid configuration: {0}")]
+    ConfigError(String),
+}
+
+pub type Result<T> = std::result::Result<T, SftpError>;
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+#[derive(Debug, Clone)]
+pub struct SftpConfig {
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    pub password: Option<String>,  // Doubles as the key passphrase when private_key is set
+    pub private_key: Option<String>,  // PEM
+    /// SHA-256 host key fingerprint (hex, colons optional); unverified when absent
+    pub host_key_fingerprint: Option<String>,
+    pub inbound_path: String,
+    pub outbound_path: String,
+    pub archive_path: Option<String>,
+    pub file_pattern: String,
+}
+
+impl SftpConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.host.is_empty() {
+            return Err(SftpError::ConfigError("host is required".to_string()));
+        }
+        if self.username.is_empty() {
+            return Err(SftpError::ConfigError("username is required".to_string()));
+        }
+        if self.password.is_none() && self.private_key.is_none() {
+            return Err(SftpError::ConfigError("password or private_key is required".to_string()));
+        }
+        if self.inbound_path.is_empty() || self.outbound_path.is_empty() {
+            return Err(SftpError::ConfigError("inbound_path and outbound_path are required".to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RemoteFile {
+    pub name: String,
+    pub path: String,
+    pub size: u64,
+    pub modified: Option<u64>,
+}
+
+// ============================================================================
+// SFTP Client
+// ============================================================================
+
+#[derive(Clone)]
+pub struct SftpClient {
+    config: SftpConfig,
+}
+
+impl SftpClient {
+    pub fn new(config: SftpConfig) -> Result<Self> {
+        config.validate()?;
+        Ok(Self { config })
+    }
+
+    pub fn config(&self) -> &SftpConfig {
+        &self.config
+    }
+
+    /// Connect, authenticate and open the inbound directory
+    pub async fn test_connection(&self) -> Result<bool> {
+        let inbound = self.config.inbound_path.clone();
+        self.run(move |sftp| {
+            sftp.readdir(Path::new(&inbound))?;
+            Ok(true)
+        })
+        .await
+    }
+
+    /// Files in the inbound directory matching the file pattern, oldest first
+    pub async fn list_inbound(&self) -> Result<Vec<RemoteFile>> {
+        let inbound = self.config.inbound_path.clone();
+        let pattern = self.config.file_pattern.clone();
+
+        self.run(move |sftp| {
+            let mut files: Vec<RemoteFile> = sftp
+                .readdir(Path::new(&inbound))?
+                .into_iter()
+                .filter(|(_, stat)| stat.is_file())
+                .filter_map(|(path, stat)| {
+                    let name = path.file_name()?.to_string_lossy().to_string();
+                    super::flat_file_format::matches_pattern(&pattern, &name).then(|| RemoteFile {
+                        name,
+                        path: path.to_string_lossy().to_string(),
+                        size: stat.size.unwrap_or(0),
+                        modified: stat.mtime,
+                    })
+                })
+                .collect();
+
+            files.sort_by(|a, b| a.modified.cmp(&b.modified).then_with(|| a.name.cmp(&b.name)));
+            Ok(files)
+        })
+        .await
+    }
+
+    pub async fn download(&self, path: &str) -> Result<Vec<u8>> {
+        let path = path.to_string();
+        self.run(move |sftp| {
+            let size = sftp.stat(Path::new(&path))?.size.unwrap_or(0);
+            if size > MAX_FILE_BYTES {
+                return Err(SftpError::FileTooLarge(path, size));
+            }
+
+            let mut contents = Vec::with_capacity(size as usize);
+            sftp.open(Path::new(&path))?.read_to_end(&mut contents)?;
+            Ok(contents)
+        })
+        .await
+    }
+
+    /// Write a file to the outbound directory. The content goes to a `.part` file first
+    /// and is renamed into place, so the ERP never picks up a half-written file.
+    pub async fn upload(&self, file_name: &str, contents: Vec<u8>) -> Result<String> {
+        let target = join(&self.config.outbound_path, file_name);
+        let partial = format!("{}.part", target);
+
+        self.run(move |sftp| {
+            let mut file = sftp.create(Path::new(&partial))?;
+            file.write_all(&contents)?;
+            drop(file);
+
+            sftp.rename(Path::new(&partial), Path::new(&target), Some(RenameFlags::OVERWRITE | RenameFlags::ATOMIC))?;
+            Ok(target)
+        })
+        .await
+    }
+
+    /// Move a processed inbound file to the archive directory (no-op without one)
+    pub async fn archive(&self, path: &str, file_name: &str) -> Result<()> {
+        let Some(archive_path) = self.config.archive_path.clone() else {
+            return Ok(());
+        };
+        let source = path.to_string();
+        let target = join(&archive_path, file_name);
+
+        self.run(move |sftp| {
+            sftp.rename(Path::new(&source), Path::new(&target), Some(RenameFlags::OVERWRITE))?;
+            Ok(())
+        })
+        .await
+    }
+
+    // ========================================================================
+    // Session Handling
+    // ========================================================================
+
+    async fn run<T, F>(&self, operation: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Sftp) -> Result<T> + Send + 'static,
+    {
+        let config = self.config.clone();
+        tokio::task::spawn_blocking(move || {
+            let (_session, sftp) = connect(&config)?;
+            operation(&sftp)
+        })
+        .await
+        .map_err(|e| SftpError::ConnectionError(format!("SFTP task failed: {}", e)))?
+    }
+}
+
+fn connect(config: &SftpConfig) -> Result<(Session, Sftp)> {
+    let address = (config.host.as_str(), config.port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| SftpError::ConnectionError(format!("Could not resolve {}", config.host)))?;
+
+    let tcp = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT)
+        .map_err(|e| SftpError::ConnectionError(format!("{}:{}: {}", config.host, config.port, e)))?;
+
+    let mut session = Session::new()?;
+    session.set_tcp_stream(tcp);
+    session.set_timeout(SESSION_TIMEOUT_MS);
+    session.handshake()?;
+
+    if let Some(expected) = &config.host_key_fingerprint {
+        let actual = session
+            .host_key_hash(HashType::Sha256)
+            .map(hex::encode)
+            .unwrap_or_default();
+        if normalize_fingerprint(expected) != actual {
+            return Err(SftpError::HostKeyMismatch { expected: expected.clone(), actual });
+        }
+    }
+
+    match (&config.private_key, &config.password) {
+        (Some(key), passphrase) => session
+            .userauth_pubkey_memory(&config.username, None, key, passphrase.as_deref())
+            .map_err(|e| SftpError::AuthError(e.message().to_string()))?,
+        (None, Some(password)) => session
+            .userauth_password(&config.username, password)
+            .map_err(|e| SftpError::AuthError(e.message().to_string()))?,
+        (None, None) => return Err(SftpError::ConfigError("password or private_key is required".to_string())),
+    }
+
+    if !session.authenticated() {
+        return Err(SftpError::AuthError("server rejected the credentials".to_string()));
+    }
+
+    let sftp = session.sftp()?;
+    Ok((session, sftp))
+}
+
+fn join(directory: &str, file_name: &str) -> String {
+    let mut path = PathBuf::from(directory);
+    path.push(file_name);
+    path.to_string_lossy().to_string()
+}
+
+/// "AB:CD:ef" -> "abcdef"
+fn normalize_fingerprint(fingerprint: &str) -> String {
+    fingerprint
+        .trim()
+        .trim_start_matches("SHA256:")
+        .chars()
+        .filter(|c| c.is_ascii_hexdigit())
+        .collect::<String>()
+        .to_lowercase()
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_validation() {
+        let config = SftpConfig {
+            host: "sftp.example.com".to_string(),
+            port: 22,
+            username: "atlas".to_string(),
+            password: None,
+            private_key: None,
+            host_key_fingerprint: None,
+            inbound_path: "/out".to_string(),
+            outbound_path: "/in".to_string(),
+            archive_path: None,
+            file_pattern: "*.csv".to_string(),
+        };
+
+        assert!(config.validate().is_err());
+        assert!(SftpConfig { password: Some("secret".to_string()), ..config }.validate().is_ok());
+    }
+
+    #[test]
+    fn test_normalize_fingerprint() {
+        assert_eq!(normalize_fingerprint("AB:CD:0f"), "abcd0f");
+        assert_eq!(normalize_fingerprint(" abcd0f "), "abcd0f");
+    }
+}
@@ -0,0 +1,231 @@
+// ERP Webhook Events
+// Normalizes NetSuite and SAP webhook payloads into stock updates
+// Pure functions: signature checks live in WebhookSecurityService, processing in ErpSyncService
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::services::erp::field_mapping::{read_field, ErpFieldMapping};
+
+/// NetSuite events that carry item stock
+pub const NETSUITE_STOCK_EVENTS: &[&str] = &["inventory_update", "inventory_updated", "item_created", "item_updated"];
+
+/// SAP events that carry material stock
+pub const SAP_STOCK_EVENTS: &[&str] = &["material_changed", "material_created"];
+
+/// One item (at one location) reported by an ERP webhook
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ErpItemUpdate {
+    /// NetSuite internal ID or SAP material number (`erp_inventory_mappings.erp_item_id`)
+    pub item_id: String,
+    /// NetSuite location ID or SAP storage location
+    pub location_id: Option<String>,
+    pub item_name: Option<String>,
+    pub ndc_code: Option<String>,
+    /// None when the event does not report stock (e.g. item master changes only)
+    pub quantity: Option<i32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebhookEvent {
+    pub event_type: String,
+    pub items: Vec<ErpItemUpdate>,
+}
+
+impl WebhookEvent {
+    /// Events other than stock events (e.g. order status) are acknowledged but not applied
+    pub fn is_stock_event(&self) -> bool {
+        NETSUITE_STOCK_EVENTS.contains(&self.event_type.as_str())
+            || SAP_STOCK_EVENTS.contains(&self.event_type.as_str())
+    }
+}
+
+// ============================================================================
+// Parsing
+// ============================================================================
+
+/// Parse a NetSuite payload:
+/// `{"event_type": "inventory_update", "data": {item} | [items]}`, where an item uses
+/// the REST record field names (`id`, `itemId`, `displayName`, `quantityOnHand`,
+/// `locations.items[]`) plus the NDC field of the connection's field mapping.
+/// Items with locations yield one update per location.
+pub fn parse_netsuite_event(payload: &Value, fields: &ErpFieldMapping) -> Result<WebhookEvent, String> {
+    let event_type = event_type(payload)?;
+    let mut items = Vec::new();
+
+    for record in records(payload) {
+        let item_id = string_field(record, &["id", "internalId"])
+            .ok_or_else(|| "NetSuite item without id".to_string())?;
+        let item_name = string_field(record, &["displayName", "itemId"]);
+        let ndc_code = read_field(record, fields.ndc_code.as_deref());
+
+        let locations = record
+            .get("locations")
+            .and_then(|l| l.get("items"))
+            .and_then(Value::as_array)
+            .filter(|l| !l.is_empty());
+
+        match locations {
+            Some(locations) => {
+                for location in locations {
+                    items.push(ErpItemUpdate {
+                        item_id: item_id.clone(),
+                        location_id: location.get("location").and_then(|l| string_field(l, &["id"])),
+                        item_name: item_name.clone(),
+                        ndc_code: ndc_code.clone(),
+                        quantity: quantity_field(location, &["quantityOnHand"]),
+                    });
+                }
+            }
+            None => items.push(ErpItemUpdate {
+                item_id,
+                location_id: string_field(record, &["locationId"]),
+                item_name,
+                ndc_code,
+                quantity: quantity_field(record, &["quantityOnHand"]),
+            }),
+        }
+    }
+
+    Ok(WebhookEvent { event_type, items })
+}
+
+/// Parse an SAP payload:
+/// `{"event_type": "material_changed", "data": {stock} | [stocks]}`, where a stock uses the
+/// OData field names (`Material`, `StorageLocation`, `MatlWrhsStkQtyInMatlBaseUnit`, `MaterialName`)
+/// plus the NDC field of the connection's field mapping
+pub fn parse_sap_event(payload: &Value, fields: &ErpFieldMapping) -> Result<WebhookEvent, String> {
+    let event_type = event_type(payload)?;
+    let mut items = Vec::new();
+
+    for record in records(payload) {
+        let item_id = string_field(record, &["Material", "Product"])
+            .ok_or_else(|| "SAP stock without Material".to_string())?;
+
+        items.push(ErpItemUpdate {
+            item_id,
+            location_id: string_field(record, &["StorageLocation"]),
+            item_name: string_field(record, &["MaterialName", "ProductDescription"]),
+            ndc_code: read_field(record, fields.ndc_code.as_deref()),
+            quantity: quantity_field(record, &["MatlWrhsStkQtyInMatlBaseUnit", "Quantity"]),
+        });
+    }
+
+    Ok(WebhookEvent { event_type, items })
+}
+
+fn event_type(payload: &Value) -> Result<String, String> {
+    string_field(payload, &["event_type", "eventType", "type"])
+        .map(|t| t.to_ascii_lowercase())
+        .ok_or_else(|| "Missing event_type".to_string())
+}
+
+/// `data` as an array, a single object, or absent
+fn records(payload: &Value) -> Vec<&Value> {
+    match payload.get("data") {
+        Some(Value::Array(records)) => records.iter().collect(),
+        Some(record @ Value::Object(_)) => vec![record],
+        _ => Vec::new(),
+    }
+}
+
+/// First non-empty field, accepting strings and numbers (ERPs send IDs as either)
+fn string_field(record: &Value, names: &[&str]) -> Option<String> {
+    names.iter().find_map(|name| match record.get(*name)? {
+        Value::String(s) if !s.trim().is_empty() => Some(s.trim().to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    })
+}
+
+/// Quantities arrive as numbers (NetSuite) or decimal strings (SAP); fractions are truncated
+fn quantity_field(record: &Value, names: &[&str]) -> Option<i32> {
+    names.iter().find_map(|name| {
+        let quantity = match record.get(*name)? {
+            Value::Number(n) => n.as_f64()?,
+            Value::String(s) => s.trim().parse::<f64>().ok()?,
+            _ => return None,
+        };
+        quantity.is_finite().then_some(quantity as i32)
+    })
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn ndc_field(field: &str) -> ErpFieldMapping {
+        ErpFieldMapping { ndc_code: Some(field.to_string()), ..Default::default() }
+    }
+
+    #[test]
+    fn test_parse_netsuite_event() {
+        let payload = json!({
+            "event_type": "inventory_update",
+            "data": [
+                {
+                    "id": 1234,
+                    "itemId": "AMOX-500",
+                    "custitem_ndc_code": "0093-4155-73",
+                    "locations": { "items": [
+                        { "location": { "id": "1" }, "quantityOnHand": 120.0 },
+                        { "location": { "id": "2" }, "quantityOnHand": 7.9 }
+                    ]}
+                },
+                { "id": "5678", "quantityOnHand": 3 }
+            ]
+        });
+
+        let event = parse_netsuite_event(&payload, &ndc_field("custitem_ndc_code")).unwrap();
+        assert!(event.is_stock_event());
+        assert_eq!(event.items.len(), 3);
+        assert_eq!(event.items[0].item_id, "1234");
+        assert_eq!(event.items[1].ndc_code.as_deref(), Some("0093-4155-73"));
+        assert_eq!(event.items[0].location_id.as_deref(), Some("1"));
+        assert_eq!(event.items[0].item_name.as_deref(), Some("AMOX-500"));
+        assert_eq!(event.items[1].quantity, Some(7));
+        assert_eq!(event.items[2].location_id, None);
+        assert_eq!(event.items[2].quantity, Some(3));
+    }
+
+    #[test]
+    fn test_parse_sap_event() {
+        let payload = json!({
+            "type": "MATERIAL_CHANGED",
+            "data": {
+                "Material": "M-100",
+                "Plant": "1000",
+                "StorageLocation": "0001",
+                "MatlWrhsStkQtyInMatlBaseUnit": "42.000",
+                "YY1_NDCCode_MDI": "0093-4155-73"
+            }
+        });
+
+        let event = parse_sap_event(&payload, &ndc_field("YY1_NDCCode_MDI")).unwrap();
+        assert_eq!(event.event_type, "material_changed");
+        assert!(event.is_stock_event());
+        assert_eq!(event.items, vec![ErpItemUpdate {
+            item_id: "M-100".to_string(),
+            location_id: Some("0001".to_string()),
+            item_name: None,
+            ndc_code: Some("0093-4155-73".to_string()),
+            quantity: Some(42),
+        }]);
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_events() {
+        let fields = ErpFieldMapping::default();
+        assert!(parse_netsuite_event(&json!({ "data": { "id": "1" } }), &fields).is_err());
+        assert!(parse_sap_event(&json!({ "event_type": "material_changed", "data": { "Plant": "1000" } }), &fields).is_err());
+
+        let order = parse_netsuite_event(&json!({ "event_type": "order_status" }), &fields).unwrap();
+        assert!(!order.is_stock_event());
+        assert!(order.items.is_empty());
+    }
+}
@@ -0,0 +1,52 @@
+/// Federated Search Service
+///
+/// One search over OpenFDA, EMA and the national regulator catalogs. Results carry their
+/// source and an equivalence key; products of the same substance on a page are grouped
+/// using ATC codes (mapped from RxNorm for OpenFDA) or normalized ingredient names.
+
+use sqlx::PgPool;
+use crate::{
+    middleware::error_handling::{AppError, Result},
+    models::federated_search::*,
+    repositories::FederatedSearchRepository,
+};
+
+const DEFAULT_LIMIT: i64 = 20;
+const MAX_LIMIT: i64 = 100;
+
+pub struct FederatedSearchService {
+    repo: FederatedSearchRepository,
+}
+
+impl FederatedSearchService {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self { repo: FederatedSearchRepository::new(db_pool) }
+    }
+
+    pub async fn search(&self, request: &FederatedSearchRequest) -> Result<FederatedSearchResponse> {
+        let query_text = request.query_text();
+        let ingredient = request.ingredient_text();
+        let atc_prefix = request.atc_prefix();
+
+        if query_text.is_none() && ingredient.is_none() && atc_prefix.is_none() {
+            return Err(AppError::BadRequest(
+                "Provide at least one of query, ingredient or atc_code".to_string()
+            ));
+        }
+
+        let sources = request.source_list()?;
+        let limit = request.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+        let offset = request.offset.unwrap_or(0).max(0);
+
+        // One extra row tells whether another page exists
+        let mut rows = self
+            .repo
+            .search(&sources, query_text, ingredient, atc_prefix.as_deref(), limit + 1, offset)
+            .await?;
+        let has_more = rows.len() as i64 > limit;
+        rows.truncate(limit as usize);
+
+        let results = rows.into_iter().map(FederatedSearchResult::from).collect();
+        Ok(FederatedSearchResponse::new(results, sources, limit, offset, has_more))
+    }
+}
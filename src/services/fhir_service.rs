@@ -0,0 +1,364 @@
+/// FHIR Service
+///
+/// Reads behind the FHIR facade (`models::fhir` maps records to resources) and the
+/// clients hospital systems use to reach it. A client exchanges its credentials for a
+/// short-lived JWT of the account that registered it, so the facade's resource routes
+/// sit behind the same auth middleware as the rest of the API.
+
+use std::collections::HashMap;
+use chrono::Utc;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use subtle::ConstantTimeEq;
+use uuid::Uuid;
+use crate::{
+    middleware::{error_handling::{AppError, Result}, JwtService},
+    models::{
+        fhir::*,
+        inventory::SearchInventoryRequest,
+        openfda::OpenFdaSearchRequest,
+        openfda_recall::extract_ndcs,
+        pharmaceutical::{PharmaceuticalResponse, SearchPharmaceuticalRequest},
+    },
+    repositories::{InventoryRepository, OpenFdaRepository, PharmaceuticalRepository, UserRepository},
+    services::UserSuspensionService,
+};
+
+/// A page of search results, and whether there may be more
+pub struct FhirPage {
+    pub resources: Vec<Value>,
+    pub has_more: bool,
+}
+
+/// Base URL of the facade: API_BASE_URL (as for OAuth callbacks) + `/fhir`
+pub fn fhir_base_url() -> String {
+    let api_base_url = std::env::var("API_BASE_URL").unwrap_or_else(|_| "http://localhost:8443".to_string());
+    format!("{}/fhir", api_base_url.trim_end_matches('/'))
+}
+
+pub struct FhirService {
+    db_pool: PgPool,
+    encryption_key: String,
+}
+
+impl FhirService {
+    pub fn new(db_pool: PgPool, encryption_key: &str) -> Self {
+        Self { db_pool, encryption_key: encryption_key.to_string() }
+    }
+
+    // ========================================================================
+    // MEDICATION
+    // ========================================================================
+
+    pub async fn read_medication(&self, id: Uuid) -> Result<Value> {
+        let pharma = PharmaceuticalRepository::new(self.db_pool.clone())
+            .find_by_id(id)
+            .await?
+            .map(PharmaceuticalResponse::from)
+            .filter(is_medication)
+            .ok_or_else(|| AppError::NotFound("Medication not found".to_string()))?;
+
+        let atc_codes = self.atc_codes(pharma.product_ndc().into_iter().collect()).await?;
+        Ok(medication(&pharma, pharma_atc_codes(&pharma, &atc_codes)))
+    }
+
+    pub async fn search_medications(&self, params: &FhirSearchParams) -> Result<FhirPage> {
+        let ndc_code = match params.code_token().map_err(unsupported_system)? {
+            Some(CodeToken::Ndc(ndc)) => Some(ndc),
+            Some(CodeToken::Atc(_)) => {
+                return Err(AppError::BadRequest("Medication is searched by NDC; use MedicationKnowledge for ATC".to_string()))
+            }
+            None => None,
+        };
+
+        let (limit, offset) = params.page();
+        let request = SearchPharmaceuticalRequest {
+            query: None,
+            brand_name: None,
+            generic_name: None,
+            manufacturer: None,
+            category: None,
+            ndc_code,
+            product_domain: None,
+            udi_di: None,
+            free_of: None,
+            limit: Some(limit),
+            offset: Some(offset),
+        };
+        let products = PharmaceuticalRepository::new(self.db_pool.clone()).search(&request).await?;
+        let has_more = products.len() as i64 == limit;
+
+        // Devices share the catalog but aren't medications
+        let medications: Vec<PharmaceuticalResponse> =
+            products.into_iter().map(PharmaceuticalResponse::from).filter(is_medication).collect();
+        let atc_codes = self.atc_codes(medications.iter().filter_map(PharmaceuticalResponse::product_ndc).collect()).await?;
+
+        Ok(FhirPage {
+            resources: medications.iter().map(|pharma| medication(pharma, pharma_atc_codes(pharma, &atc_codes))).collect(),
+            has_more,
+        })
+    }
+
+    // ========================================================================
+    // MEDICATION KNOWLEDGE
+    // ========================================================================
+
+    /// Read by product NDC (the resource id)
+    pub async fn read_medication_knowledge(&self, product_ndc: &str) -> Result<Value> {
+        let entry = OpenFdaRepository::new(self.db_pool.clone())
+            .find_by_ndc(product_ndc)
+            .await?
+            .ok_or_else(|| AppError::NotFound("MedicationKnowledge not found".to_string()))?;
+
+        let atc_codes = self.atc_codes(vec![entry.product_ndc.clone()]).await?;
+        let entry_atc_codes = atc_codes.get(&entry.product_ndc).map(Vec::as_slice).unwrap_or_default();
+        Ok(medication_knowledge(&entry, entry_atc_codes, Utc::now().date_naive()))
+    }
+
+    pub async fn search_medication_knowledge(&self, params: &FhirSearchParams) -> Result<FhirPage> {
+        let repo = OpenFdaRepository::new(self.db_pool.clone());
+        let (limit, offset) = params.page();
+
+        let entries = match params.code_token().map_err(unsupported_system)? {
+            // Package NDCs find their product
+            Some(CodeToken::Ndc(ndc)) => {
+                let product_ndc = extract_ndcs(&ndc).into_iter().next().map(|(product_ndc, _)| product_ndc).unwrap_or(ndc);
+                match repo.find_by_ndc(&product_ndc).await? {
+                    Some(entry) if offset == 0 => vec![entry],
+                    _ => Vec::new(),
+                }
+            }
+            Some(CodeToken::Atc(prefix)) => repo.find_by_atc_prefix(&prefix, limit, offset).await?,
+            None => {
+                let request = OpenFdaSearchRequest { query: None, facets: None, limit: Some(limit), offset: Some(offset) };
+                repo.search(&request).await?
+            }
+        };
+        let has_more = entries.len() as i64 == limit;
+
+        let atc_codes = self.atc_codes(entries.iter().map(|entry| entry.product_ndc.clone()).collect()).await?;
+        let today = Utc::now().date_naive();
+        let resources = entries
+            .iter()
+            .map(|entry| {
+                let entry_atc_codes = atc_codes.get(&entry.product_ndc).map(Vec::as_slice).unwrap_or_default();
+                medication_knowledge(entry, entry_atc_codes, today)
+            })
+            .collect();
+
+        Ok(FhirPage { resources, has_more })
+    }
+
+    // ========================================================================
+    // INVENTORY ITEM
+    // ========================================================================
+
+    /// A listing on the marketplace (taken-down and unavailable listings aren't served)
+    pub async fn read_inventory_item(&self, id: Uuid) -> Result<Value> {
+        let listing = InventoryRepository::new(self.db_pool.clone())
+            .find_listed_with_details(&[id])
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| AppError::NotFound("InventoryItem not found".to_string()))?;
+
+        Ok(inventory_item(&listing))
+    }
+
+    pub async fn search_inventory_items(&self, params: &FhirSearchParams) -> Result<FhirPage> {
+        let ndc_code = match params.code_token().map_err(unsupported_system)? {
+            Some(CodeToken::Ndc(ndc)) => Some(ndc),
+            Some(CodeToken::Atc(_)) => return Err(AppError::BadRequest("InventoryItem is searched by NDC".to_string())),
+            None => None,
+        };
+
+        let (limit, offset) = params.page();
+        let request = SearchInventoryRequest {
+            q: None,
+            pharmaceutical_id: None,
+            brand_name: None,
+            generic_name: None,
+            manufacturer: None,
+            ndc_code,
+            expiry_before: None,
+            expiry_after: None,
+            min_quantity: None,
+            max_quantity: None,
+            status: None,
+            min_price: None,
+            max_price: None,
+            product_domain: None,
+            free_of: None,
+            facets: None,
+            limit: Some(limit),
+            offset: Some(offset),
+            sort_by: None,
+            sort_order: None,
+        };
+        let listings = InventoryRepository::new(self.db_pool.clone()).search_with_details(&request).await?;
+
+        Ok(FhirPage {
+            has_more: listings.len() as i64 == limit,
+            resources: listings.iter().map(inventory_item).collect(),
+        })
+    }
+
+    /// ATC codes by product NDC
+    async fn atc_codes(&self, product_ndcs: Vec<String>) -> Result<HashMap<String, Vec<String>>> {
+        if product_ndcs.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let mut by_ndc: HashMap<String, Vec<String>> = HashMap::new();
+        for (product_ndc, atc_code) in OpenFdaRepository::new(self.db_pool.clone()).find_atc_codes(&product_ndcs).await? {
+            by_ndc.entry(product_ndc).or_default().push(atc_code);
+        }
+        Ok(by_ndc)
+    }
+
+    // ========================================================================
+    // CLIENTS
+    // ========================================================================
+
+    pub async fn create_client(&self, user_id: Uuid, request: CreateFhirClientRequest) -> Result<FhirClientWithSecret> {
+        let name = request.name.trim();
+        if name.is_empty() || name.chars().count() > 255 {
+            return Err(AppError::BadRequest("name must be 1 to 255 characters".to_string()));
+        }
+
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM fhir_clients WHERE user_id = $1 AND revoked_at IS NULL")
+            .bind(user_id)
+            .fetch_one(&self.db_pool)
+            .await?;
+        if count >= MAX_CLIENTS_PER_USER {
+            return Err(AppError::BadRequest(format!(
+                "At most {} FHIR clients can be registered",
+                MAX_CLIENTS_PER_USER
+            )));
+        }
+
+        let client_secret = format!("fhirsec_{}", random_hex(32));
+        let client = sqlx::query_as::<_, FhirClient>(
+            r#"
+            INSERT INTO fhir_clients (user_id, name, client_id, secret_hash)
+            VALUES ($1, $2, $3, $4)
+            RETURNING *
+            "#
+        )
+        .bind(user_id)
+        .bind(name)
+        .bind(format!("fhir_{}", random_hex(12)))
+        .bind(hash_secret(&client_secret))
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        tracing::info!("FHIR client {} registered by user {}", client.client_id, user_id);
+
+        Ok(FhirClientWithSecret { client, client_secret })
+    }
+
+    pub async fn list_clients(&self, user_id: Uuid) -> Result<Vec<FhirClient>> {
+        let clients = sqlx::query_as::<_, FhirClient>(
+            "SELECT * FROM fhir_clients WHERE user_id = $1 ORDER BY created_at DESC"
+        )
+        .bind(user_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(clients)
+    }
+
+    /// Tokens already issued to the client stay valid until they expire (minutes)
+    pub async fn revoke_client(&self, id: Uuid, user_id: Uuid) -> Result<FhirClient> {
+        let client = sqlx::query_as::<_, FhirClient>(
+            r#"
+            UPDATE fhir_clients SET revoked_at = NOW()
+            WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL
+            RETURNING *
+            "#
+        )
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("FHIR client not found".to_string()))?;
+
+        tracing::info!("FHIR client {} revoked by user {}", client.client_id, user_id);
+
+        Ok(client)
+    }
+
+    /// Exchange client credentials for a read token of the client's account, valid on `/fhir` only
+    pub async fn issue_token(
+        &self,
+        jwt_service: &JwtService,
+        client_id: &str,
+        client_secret: &str,
+        scope: String,
+    ) -> Result<SmartTokenResponse> {
+        let client = sqlx::query_as::<_, FhirClient>(
+            "SELECT * FROM fhir_clients WHERE client_id = $1 AND revoked_at IS NULL"
+        )
+        .bind(client_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or(AppError::Unauthorized)?;
+
+        if !bool::from(hash_secret(client_secret).as_bytes().ct_eq(client.secret_hash.as_bytes())) {
+            tracing::warn!("FHIR token request with a wrong secret for client {}", client.client_id);
+            return Err(AppError::Unauthorized);
+        }
+
+        UserSuspensionService::new(self.db_pool.clone())
+            .ensure_not_suspended(client.user_id)
+            .await?;
+        let user = UserRepository::new(self.db_pool.clone(), &self.encryption_key)?
+            .find_by_id(client.user_id)
+            .await?
+            .ok_or(AppError::Unauthorized)?;
+
+        // Limited to /fhir and issued without the account's admin rights
+        let access_token = jwt_service.generate_fhir_token(
+            user.id,
+            &user.email,
+            &user.company_name,
+            user.is_verified,
+            FHIR_TOKEN_TTL_SECS,
+        )?;
+
+        sqlx::query("UPDATE fhir_clients SET last_used_at = NOW() WHERE id = $1")
+            .bind(client.id)
+            .execute(&self.db_pool)
+            .await?;
+
+        Ok(SmartTokenResponse {
+            access_token,
+            token_type: "bearer".to_string(),
+            expires_in: FHIR_TOKEN_TTL_SECS,
+            scope,
+        })
+    }
+}
+
+fn unsupported_system(system: String) -> AppError {
+    AppError::BadRequest(format!("Unsupported code system {}", system))
+}
+
+fn pharma_atc_codes<'a>(pharma: &PharmaceuticalResponse, atc_codes: &'a HashMap<String, Vec<String>>) -> &'a [String] {
+    pharma.product_ndc()
+        .and_then(|ndc| atc_codes.get(&ndc))
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+}
+
+fn random_hex(bytes: usize) -> String {
+    use rand::RngCore;
+    let mut buffer = vec![0u8; bytes];
+    rand::thread_rng().fill_bytes(&mut buffer);
+    hex::encode(buffer)
+}
+
+fn hash_secret(secret: &str) -> String {
+    hex::encode(Sha256::digest(secret.as_bytes()))
+}
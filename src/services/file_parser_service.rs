@@ -1,5 +1,6 @@
 /// Enterprise-grade file parser for pharmaceutical inventory imports
 /// Supports: CSV, Excel (XLSX/XLS), JSON with intelligent format detection
+/// PDFs and images are recognized here but read by document extraction
 
 use std::io::Cursor;
 use csv::ReaderBuilder;
@@ -37,6 +38,8 @@ pub enum FileType {
     Csv,
     Excel,
     Json,
+    /// PDF or image, read by document extraction rather than parsed into rows
+    Document,
 }
 
 impl std::fmt::Display for FileType {
@@ -45,6 +48,7 @@ impl std::fmt::Display for FileType {
             FileType::Csv => write!(f, "csv"),
             FileType::Excel => write!(f, "xlsx"),
             FileType::Json => write!(f, "json"),
+            FileType::Document => write!(f, "document"),
         }
     }
 }
@@ -79,6 +83,11 @@ impl FileParserService {
             FileType::Csv => Self::parse_csv(file_data, filename)?,
             FileType::Excel => Self::parse_excel(file_data, filename)?,
             FileType::Json => Self::parse_json(file_data, filename)?,
+            FileType::Document => {
+                return Err(AppError::InvalidInput(
+                    "PDFs and images are imported through document extraction, not row parsing.".to_string()
+                ));
+            }
         };
 
         parsed.file_type = file_type;
@@ -87,10 +96,43 @@ impl FileParserService {
         Ok(parsed)
     }
 
+    /// Media type of a PDF or image upload (as sent to Claude), from magic bytes or extension
+    pub fn document_media_type(data: &[u8], filename: &str) -> Option<&'static str> {
+        if data.starts_with(b"%PDF-") {
+            return Some("application/pdf");
+        }
+        if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+            return Some("image/png");
+        }
+        if data.starts_with(b"\xFF\xD8\xFF") {
+            return Some("image/jpeg");
+        }
+        if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+            return Some("image/gif");
+        }
+        if data.len() >= 12 && &data[0..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+            return Some("image/webp");
+        }
+
+        let filename_lower = filename.to_lowercase();
+        match filename_lower.rsplit('.').next() {
+            Some("pdf") => Some("application/pdf"),
+            Some("png") => Some("image/png"),
+            Some("jpg") | Some("jpeg") => Some("image/jpeg"),
+            Some("webp") => Some("image/webp"),
+            Some("gif") => Some("image/gif"),
+            _ => None,
+        }
+    }
+
     /// Detect file type from content and filename
     fn detect_file_type(data: &[u8], filename: &str) -> Result<FileType> {
         let filename_lower = filename.to_lowercase();
 
+        if Self::document_media_type(data, filename).is_some() {
+            return Ok(FileType::Document);
+        }
+
         // Check file extension first
         if filename_lower.ends_with(".csv") || filename_lower.ends_with(".txt") {
             return Ok(FileType::Csv);
@@ -132,7 +174,7 @@ impl FileParserService {
         }
 
         Err(AppError::InvalidInput(
-            "Unsupported file format. Please upload CSV, Excel (XLSX/XLS), or JSON files.".to_string()
+            "Unsupported file format. Please upload CSV, Excel (XLSX/XLS), JSON, PDF or image files.".to_string()
         ))
     }
 
@@ -565,4 +607,13 @@ mod tests {
         assert!(result.headers.len() >= 3);
         assert_eq!(result.rows.len(), 1);
     }
+
+    #[test]
+    fn test_document_media_type() {
+        assert_eq!(FileParserService::document_media_type(b"%PDF-1.7\n", "coa"), Some("application/pdf"));
+        assert_eq!(FileParserService::document_media_type(b"\xFF\xD8\xFF\xE0", "scan.bin"), Some("image/jpeg"));
+        assert_eq!(FileParserService::document_media_type(b"", "Packing List.PNG"), Some("image/png"));
+        assert_eq!(FileParserService::document_media_type(b"NDC,Qty\n", "stock.csv"), None);
+        assert!(FileParserService::parse(b"%PDF-1.4\n", "coa.pdf").is_err());
+    }
 }
@@ -0,0 +1,296 @@
+/// Health Service
+///
+/// Liveness and readiness checks for monitoring and orchestrators. Readiness checks
+/// every dependency concurrently, each under a timeout, and reports its status and
+/// latency:
+/// - database: `SELECT 1` on the pool (essential)
+/// - encryption_key: the configured key encrypts and decrypts (essential)
+/// - file_storage: the storage directory is writable and has free space (essential;
+///   degraded below HEALTH_DISK_WARN_MB, down below HEALTH_DISK_CRITICAL_MB)
+/// - claude_api: the Claude API answers (cached for a minute; not configured is ok)
+/// - erp_scheduler: this process's ERP sync scheduler has ticked recently
+/// - database_replica: the read replica is reachable and within its lag limit
+///   (not configured is ok; reads fall back to the primary otherwise)
+///
+/// Failing essential dependencies make the instance down; the others only degrade it.
+
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use sqlx::PgPool;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+use crate::{
+    config::{AppConfig, ReadReplica},
+    models::health::*,
+    services::{
+        claude_ai_service::{claude_api_url, CLAUDE_VERSION},
+        erp::ErpSyncScheduler,
+        EncryptionService,
+    },
+};
+
+/// Time each dependency check may take
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long a Claude API reachability result is reused
+const CLAUDE_CHECK_CACHE: Duration = Duration::from_secs(60);
+
+/// Minimum age before an ERP scheduler heartbeat counts as stale
+const MIN_SCHEDULER_STALE_MINUTES: i64 = 10;
+
+static CLAUDE_CHECK: Lazy<Mutex<Option<(Instant, DependencyCheck)>>> = Lazy::new(|| Mutex::new(None));
+
+pub struct HealthService {
+    db_pool: PgPool,
+    encryption_key: String,
+    file_storage_path: PathBuf,
+    read_replica: Option<ReadReplica>,
+}
+
+impl HealthService {
+    pub fn new(config: &AppConfig) -> Self {
+        Self {
+            db_pool: config.database_pool.clone(),
+            encryption_key: config.encryption_key.clone(),
+            file_storage_path: PathBuf::from(&config.file_storage_path),
+            read_replica: config.read_replica.clone(),
+        }
+    }
+
+    /// The process is up and serving requests; no dependencies are checked
+    pub fn liveness() -> HealthReport {
+        HealthReport::new(Vec::new())
+    }
+
+    /// Check every dependency
+    pub async fn readiness(&self) -> HealthReport {
+        let (database, encryption, storage, claude) = tokio::join!(
+            timed("database", true, self.check_database()),
+            timed("encryption_key", true, self.check_encryption_key()),
+            timed("file_storage", true, self.check_file_storage()),
+            timed("claude_api", false, check_claude_api()),
+        );
+
+        HealthReport::new(vec![
+            database,
+            encryption,
+            storage,
+            claude,
+            check_erp_scheduler(),
+            self.check_read_replica(),
+        ])
+    }
+
+    async fn check_database(&self) -> DependencyCheck {
+        let started = Instant::now();
+        match sqlx::query_scalar::<_, i32>("SELECT 1").fetch_one(&self.db_pool).await {
+            Ok(_) => DependencyCheck::ok("database", true, elapsed_ms(started)).with_details(serde_json::json!({
+                "pool_size": self.db_pool.size(),
+                "idle_connections": self.db_pool.num_idle(),
+            })),
+            Err(e) => {
+                tracing::warn!("Health check: database query failed: {}", e);
+                DependencyCheck::failed("database", true, Some(elapsed_ms(started)), "Database query failed")
+            }
+        }
+    }
+
+    /// From the lag monitor's latest check; doesn't query the replica
+    fn check_read_replica(&self) -> DependencyCheck {
+        let Some(replica) = &self.read_replica else {
+            return DependencyCheck {
+                name: "database_replica",
+                status: HealthStatus::Ok,
+                critical: false,
+                latency_ms: None,
+                message: Some("Not configured; all reads use the primary".to_string()),
+                details: None,
+            };
+        };
+
+        let status = replica.status();
+        let details = serde_json::json!({
+            "lag_seconds": status.lag.as_secs_f64(),
+            "max_lag_seconds": status.max_lag.as_secs(),
+        });
+
+        if !status.reachable {
+            DependencyCheck::failed("database_replica", false, None, "Replica unreachable; reads use the primary")
+        } else if !status.in_use {
+            DependencyCheck::failed(
+                "database_replica",
+                false,
+                None,
+                format!("Replica is {}s behind; reads use the primary", status.lag.as_secs()),
+            )
+            .with_details(details)
+        } else {
+            DependencyCheck {
+                name: "database_replica",
+                status: HealthStatus::Ok,
+                critical: false,
+                latency_ms: None,
+                message: None,
+                details: Some(details),
+            }
+        }
+    }
+
+    async fn check_encryption_key(&self) -> DependencyCheck {
+        let started = Instant::now();
+        let probe = "atlas-health-check";
+        let round_trip = EncryptionService::new(&self.encryption_key)
+            .and_then(|service| service.encrypt(probe).and_then(|encrypted| service.decrypt(&encrypted)));
+
+        match round_trip {
+            Ok(decrypted) if decrypted == probe => DependencyCheck::ok("encryption_key", true, elapsed_ms(started)),
+            Ok(_) => DependencyCheck::failed("encryption_key", true, Some(elapsed_ms(started)), "Encryption round trip mismatch"),
+            Err(_) => DependencyCheck::failed("encryption_key", true, Some(elapsed_ms(started)), "Encryption key is unusable"),
+        }
+    }
+
+    async fn check_file_storage(&self) -> DependencyCheck {
+        let started = Instant::now();
+        let probe = self.file_storage_path.join(format!(".health-{}", Uuid::new_v4()));
+
+        let writable = async {
+            tokio::fs::create_dir_all(&self.file_storage_path).await?;
+            tokio::fs::write(&probe, b"ok").await?;
+            tokio::fs::remove_file(&probe).await
+        }
+        .await;
+        if let Err(e) = writable {
+            tracing::warn!("Health check: file storage {} is not writable: {}", self.file_storage_path.display(), e);
+            return DependencyCheck::failed("file_storage", true, Some(elapsed_ms(started)), "File storage is not writable");
+        }
+
+        let space = fs2::available_space(&self.file_storage_path)
+            .and_then(|available| Ok((available, fs2::total_space(&self.file_storage_path)?)));
+        let (available, total) = match space {
+            Ok(space) => space,
+            Err(e) => {
+                tracing::warn!("Health check: could not read free space of {}: {}", self.file_storage_path.display(), e);
+                return DependencyCheck::ok("file_storage", true, elapsed_ms(started))
+                    .with_status(HealthStatus::Degraded, "Free disk space unknown");
+            }
+        };
+
+        let available_mb = available / (1024 * 1024);
+        let check = DependencyCheck::ok("file_storage", true, elapsed_ms(started)).with_details(serde_json::json!({
+            "available_mb": available_mb,
+            "total_mb": total / (1024 * 1024),
+        }));
+
+        if available_mb < env_u64("HEALTH_DISK_CRITICAL_MB", 256) {
+            check.with_status(HealthStatus::Down, format!("Only {} MB of disk space left", available_mb))
+        } else if available_mb < env_u64("HEALTH_DISK_WARN_MB", 2048) {
+            check.with_status(HealthStatus::Degraded, format!("Low disk space: {} MB left", available_mb))
+        } else {
+            check
+        }
+    }
+}
+
+/// Run a check under CHECK_TIMEOUT
+async fn timed(
+    name: &'static str,
+    critical: bool,
+    check: impl std::future::Future<Output = DependencyCheck>,
+) -> DependencyCheck {
+    match tokio::time::timeout(CHECK_TIMEOUT, check).await {
+        Ok(result) => result,
+        Err(_) => DependencyCheck::failed(name, critical, Some(CHECK_TIMEOUT.as_millis() as u64), "Timed out"),
+    }
+}
+
+/// Any HTTP answer from the API counts as reachable; a rejected key degrades
+async fn check_claude_api() -> DependencyCheck {
+    if let Some((checked_at, check)) = CLAUDE_CHECK.lock().ok().and_then(|cached| cached.clone()) {
+        if checked_at.elapsed() < CLAUDE_CHECK_CACHE {
+            return check;
+        }
+    }
+
+    let Ok(api_key) = std::env::var("ANTHROPIC_API_KEY") else {
+        return DependencyCheck {
+            name: "claude_api",
+            status: HealthStatus::Ok,
+            critical: false,
+            latency_ms: None,
+            message: Some("Not configured; AI features are disabled".to_string()),
+            details: None,
+        };
+    };
+
+    let client = reqwest::Client::builder()
+        .timeout(CHECK_TIMEOUT)
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .unwrap_or_default();
+
+    let started = Instant::now();
+    let check = match client
+        .get(claude_api_url())
+        .header("x-api-key", api_key)
+        .header("anthropic-version", CLAUDE_VERSION)
+        .send()
+        .await
+    {
+        Ok(response) if matches!(response.status().as_u16(), 401 | 403) => {
+            DependencyCheck::failed("claude_api", false, Some(elapsed_ms(started)), "API key was rejected")
+        }
+        Ok(_) => DependencyCheck::ok("claude_api", false, elapsed_ms(started)),
+        Err(e) => {
+            tracing::warn!("Health check: Claude API unreachable: {}", e);
+            DependencyCheck::failed("claude_api", false, Some(elapsed_ms(started)), "Claude API is unreachable")
+        }
+    };
+
+    if let Ok(mut cached) = CLAUDE_CHECK.lock() {
+        *cached = Some((Instant::now(), check.clone()));
+    }
+    check
+}
+
+fn check_erp_scheduler() -> DependencyCheck {
+    let Some(heartbeat) = ErpSyncScheduler::heartbeat() else {
+        return DependencyCheck::failed("erp_scheduler", false, None, "Scheduler has not started");
+    };
+
+    let age_seconds = (Utc::now() - heartbeat.last_tick_at).num_seconds().max(0);
+    let stale_after = (3 * heartbeat.poll_minutes as i64).max(MIN_SCHEDULER_STALE_MINUTES) * 60;
+    let details = serde_json::json!({
+        "last_tick_at": heartbeat.last_tick_at,
+        "seconds_since_tick": age_seconds,
+        "poll_minutes": heartbeat.poll_minutes,
+    });
+
+    if age_seconds > stale_after {
+        DependencyCheck::failed(
+            "erp_scheduler",
+            false,
+            None,
+            format!("No scheduler tick for {} minutes", age_seconds / 60),
+        )
+        .with_details(details)
+    } else {
+        DependencyCheck {
+            name: "erp_scheduler",
+            status: HealthStatus::Ok,
+            critical: false,
+            latency_ms: None,
+            message: None,
+            details: Some(details),
+        }
+    }
+}
+
+fn elapsed_ms(started: Instant) -> u64 {
+    started.elapsed().as_millis() as u64
+}
+
+fn env_u64(name: &str, default: u64) -> u64 {
+    std::env::var(name).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+}
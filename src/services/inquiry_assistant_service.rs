@@ -5,11 +5,14 @@
 /// - Generating contextual response suggestions
 /// - Providing negotiation strategies
 /// - Maintaining conversation tone and professionalism
+/// - Remembering earlier suggestions per inquiry (and whether the seller sent, edited or
+///   rejected them) so follow-ups do not repeat offers already made or turned down
 
 use crate::{
     middleware::error_handling::{Result, AppError},
     models::inquiry_assistant::*,
-    services::claude_ai_service::{ClaudeAIService, ClaudeRequestConfig, user_message},
+    services::claude_ai_service::{ClaudeAIService, ClaudeMessage, ClaudeRequestConfig, assistant_message, user_message},
+    services::ai_response_cache_service::AiFeature,
 };
 use sqlx::PgPool;
 use uuid::Uuid;
@@ -29,8 +32,9 @@ GUIDELINES:
 RESPONSE FORMAT:
 Return ONLY a JSON object:
 {
-  "response": "The suggested message text",
-  "reasoning": "Why this approach is effective",
+  "response": "The suggested message text, in the language requested",
+  "english_translation": "English translation of the response (only when the response is not in English)",
+  "reasoning": "Why this approach is effective (in English)",
   "negotiation_tips": ["Tip 1", "Tip 2"]
 }
 
@@ -41,6 +45,10 @@ TONE GUIDELINES:
 - Closing: Confident, clear next steps
 - Rejections: Respectful, leave door open for future
 
+Earlier turns of this conversation are your previous suggestions for the same inquiry,
+each followed by what the seller did with it. Build on offers the seller sent, do not repeat
+offers the seller rejected, and respect the reasons given.
+
 Remember: You're building long-term business relationships, not just closing single deals.
 "#;
 
@@ -65,7 +73,13 @@ impl InquiryAssistantService {
         user_id: Uuid,
         suggestion_type: SuggestionType,
         custom_instructions: Option<String>,
+        language: Option<String>,
+        use_memory: bool,
     ) -> Result<InquiryAiSuggestion> {
+        if let Some(code) = language.as_deref().filter(|c| normalize_language(c).is_none()) {
+            return Err(AppError::BadRequest(format!("Unsupported language '{}'", code)));
+        }
+
         // 1. Verify user owns this inquiry (as seller)
         let inquiry_ownership = sqlx::query!(
             r#"
@@ -99,15 +113,45 @@ impl InquiryAssistantService {
         // 4. Load conversation history
         let conversation_history = self.load_conversation_history(inquiry_id).await?;
 
-        // 5. Build prompt
+        // 5. Choose the language: requested, buyer's preference, buyer's messages, seller's default
+        let preferences = self.get_language_preferences(user_id).await?;
+        let (initial_message, buyer_language) = self.load_buyer_language_inputs(inquiry_id).await?;
+        let buyer_messages: Vec<&str> = initial_message
+            .iter()
+            .map(String::as_str)
+            .chain(
+                conversation_history.messages.iter()
+                    .filter(|m| m.sender == "buyer")
+                    .map(|m| m.text.as_str())
+            )
+            .collect();
+        let (language, language_source) = choose_language(
+            language.as_deref(),
+            buyer_language.as_deref(),
+            &buyer_messages,
+            Some(&preferences.negotiation_language),
+        );
+        let include_translation = preferences.include_translation && language != "en";
+
+        // 6. Build prompt
         let prompt = self.build_prompt(
             &context,
             &conversation_history,
             &suggestion_type,
             custom_instructions.as_deref(),
+            language,
+            include_translation,
         );
 
-        // 6. Call Claude
+        // 7. Replay the assistant thread: recent turns verbatim, older ones condensed
+        let turns = if use_memory {
+            self.load_thread(inquiry_id, user_id).await?
+        } else {
+            Vec::new()
+        };
+        let messages = build_memory_messages(&turns, prompt);
+
+        // 8. Call Claude and validate the response
         let config = ClaudeRequestConfig {
             max_tokens: 1024,
             temperature: Some(0.7), // Balanced for professional yet natural responses
@@ -115,54 +159,34 @@ impl InquiryAssistantService {
         };
 
         let suggestion_id = Uuid::new_v4();
-        let claude_response = self.claude_service.send_message(
-            vec![user_message(prompt)],
+        let validated = self.claude_service.send_message_validated::<AiInquiryResponse>(
+            messages,
             config,
             user_id,
             Some(suggestion_id),
+            AiFeature::InquiryAssistant,
         ).await?;
+        let claude_response = validated.response;
+        let response_text = validated.value.response.trim().to_string();
+        let reasoning = validated.value.reasoning;
+        let english_translation = validated.value.english_translation
+            .map(|t| t.trim().to_string())
+            .filter(|t| include_translation && !t.is_empty());
+        if include_translation && english_translation.is_none() {
+            tracing::warn!("Inquiry suggestion in '{}' returned without an English translation", language);
+        }
+        self.claude_service.cache_response(&claude_response).await;
 
-        // 7. Parse AI response (strip markdown code fences if present)
-        let content = claude_response.content.trim();
-        let json_content = if content.starts_with("```json") {
-            content.trim_start_matches("```json")
-                   .trim_start_matches("```")
-                   .trim_end_matches("```")
-                   .trim()
-        } else if content.starts_with("```") {
-            content.trim_start_matches("```")
-                   .trim_end_matches("```")
-                   .trim()
-        } else {
-            content
-        };
-
-        let ai_response: serde_json::Value = serde_json::from_str(json_content)
-            .map_err(|e| {
-                tracing::error!("Failed to parse AI response: {}", e);
-                tracing::error!("Raw response: {}", claude_response.content);
-                tracing::error!("Cleaned content: {}", json_content);
-                AppError::Internal(anyhow::anyhow!("AI returned invalid response"))
-            })?;
-
-        let response_text = ai_response["response"]
-            .as_str()
-            .ok_or_else(|| AppError::Internal(anyhow::anyhow!("Missing response field")))?
-            .to_string();
-
-        let reasoning = ai_response["reasoning"]
-            .as_str()
-            .map(|s| s.to_string());
-
-        // 8. Save suggestion to database
+        // 9. Save suggestion to database
         let suggestion = sqlx::query_as!(
             InquiryAiSuggestion,
             r#"
             INSERT INTO inquiry_ai_suggestions (
                 id, inquiry_id, user_id, suggestion_type, suggestion_text,
-                context_snapshot, ai_reasoning, ai_cost_usd, ai_tokens_used
+                context_snapshot, ai_reasoning, ai_cost_usd, ai_tokens_used,
+                language, language_source, english_translation
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
             RETURNING *
             "#,
             suggestion_id,
@@ -173,12 +197,36 @@ impl InquiryAssistantService {
             serde_json::to_value(&context).ok(),
             reasoning,
             rust_decimal::Decimal::try_from(claude_response.cost_usd).unwrap_or_default(),
-            claude_response.input_tokens as i32 + claude_response.output_tokens as i32
+            claude_response.input_tokens as i32 + claude_response.output_tokens as i32,
+            language,
+            language_source.as_str(),
+            english_translation
         )
         .fetch_one(&self.db_pool)
         .await?;
 
-        // 9. Increment usage quota
+        // 10. Record the turn in the assistant thread
+        let request_text = describe_request(&suggestion.suggestion_type, custom_instructions.as_deref());
+        sqlx::query(
+            r#"
+            INSERT INTO inquiry_assistant_turns (
+                inquiry_id, user_id, suggestion_id, suggestion_type,
+                request_text, response_text, estimated_tokens
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#
+        )
+        .bind(inquiry_id)
+        .bind(user_id)
+        .bind(suggestion.id)
+        .bind(&suggestion.suggestion_type)
+        .bind(&request_text)
+        .bind(&suggestion.suggestion_text)
+        .bind(estimate_tokens(&request_text) + estimate_tokens(&suggestion.suggestion_text))
+        .execute(&self.db_pool)
+        .await?;
+
+        // 11. Increment usage quota
         sqlx::query!(
             r#"
             INSERT INTO user_ai_usage_limits (user_id, monthly_inquiry_assists_used)
@@ -194,10 +242,11 @@ impl InquiryAssistantService {
         .await?;
 
         tracing::info!(
-            "Inquiry suggestion generated: inquiry={}, type={}, user={}, cost=${}",
+            "Inquiry suggestion generated: inquiry={}, type={}, user={}, memory_turns={}, cost=${}",
             inquiry_id,
             suggestion_type.to_string(),
             user_id,
+            turns.len(),
             claude_response.cost_usd
         );
 
@@ -285,6 +334,82 @@ impl InquiryAssistantService {
         })
     }
 
+    /// Buyer's initial inquiry message and preferred language
+    async fn load_buyer_language_inputs(&self, inquiry_id: Uuid) -> Result<(Option<String>, Option<String>)> {
+        let row: Option<(Option<String>, Option<String>)> = sqlx::query_as(
+            r#"
+            SELECT i.message, lp.preferred_language
+            FROM inquiries i
+            LEFT JOIN user_language_preferences lp ON lp.user_id = i.buyer_id
+            WHERE i.id = $1
+            "#
+        )
+        .bind(inquiry_id)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        Ok(row.unwrap_or((None, None)))
+    }
+
+    /// User's language preferences, or the defaults if none are saved
+    pub async fn get_language_preferences(&self, user_id: Uuid) -> Result<LanguagePreferences> {
+        let preferences = sqlx::query_as::<_, LanguagePreferences>(
+            r#"
+            SELECT preferred_language, negotiation_language, include_translation
+            FROM user_language_preferences
+            WHERE user_id = $1
+            "#
+        )
+        .bind(user_id)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        Ok(preferences.unwrap_or_default())
+    }
+
+    /// Save language preferences; omitted fields keep their current value
+    pub async fn update_language_preferences(
+        &self,
+        user_id: Uuid,
+        request: &UpdateLanguagePreferencesRequest,
+    ) -> Result<LanguagePreferences> {
+        let validate = |code: &Option<String>| -> Result<Option<&'static str>> {
+            match code.as_deref() {
+                None => Ok(None),
+                Some(c) => normalize_language(c).map(Some).ok_or_else(|| {
+                    AppError::BadRequest(format!(
+                        "Unsupported language '{}'. Supported: {}",
+                        c,
+                        SUPPORTED_LANGUAGES.iter().map(|(code, _)| *code).collect::<Vec<_>>().join(", ")
+                    ))
+                }),
+            }
+        };
+        let preferred_language = validate(&request.preferred_language)?;
+        let negotiation_language = validate(&request.negotiation_language)?;
+
+        let preferences = sqlx::query_as::<_, LanguagePreferences>(
+            r#"
+            INSERT INTO user_language_preferences (user_id, preferred_language, negotiation_language, include_translation)
+            VALUES ($1, $2, COALESCE($3, 'en'), COALESCE($4, TRUE))
+            ON CONFLICT (user_id) DO UPDATE SET
+                preferred_language = COALESCE($2, user_language_preferences.preferred_language),
+                negotiation_language = COALESCE($3, user_language_preferences.negotiation_language),
+                include_translation = COALESCE($4, user_language_preferences.include_translation),
+                updated_at = NOW()
+            RETURNING preferred_language, negotiation_language, include_translation
+            "#
+        )
+        .bind(user_id)
+        .bind(preferred_language)
+        .bind(negotiation_language)
+        .bind(request.include_translation)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        Ok(preferences)
+    }
+
     /// Build AI prompt with full context
     fn build_prompt(
         &self,
@@ -292,6 +417,8 @@ impl InquiryAssistantService {
         history: &ConversationHistory,
         suggestion_type: &SuggestionType,
         custom_instructions: Option<&str>,
+        language: &str,
+        include_translation: bool,
     ) -> String {
         let mut prompt = format!(
             r#"INQUIRY CONTEXT:
@@ -376,6 +503,15 @@ Message Count: {}
             prompt.push_str(&format!("\nADDITIONAL INSTRUCTIONS: {}\n", instructions));
         }
 
+        prompt.push_str(&format!(
+            "\nLANGUAGE: Write the response in {} ({}), the language the buyer communicates in.\n",
+            language_name(language),
+            language
+        ));
+        if include_translation {
+            prompt.push_str("Include an \"english_translation\" field with a faithful English translation of the response.\n");
+        }
+
         prompt.push_str("\nGenerate a professional response that the seller can use (possibly with minor edits).");
 
         prompt
@@ -439,6 +575,20 @@ Message Count: {}
         .execute(&self.db_pool)
         .await?;
 
+        // 6. Remember what was sent for follow-up suggestions
+        sqlx::query(
+            r#"
+            UPDATE inquiry_assistant_turns
+            SET outcome = $1, sent_text = $2, updated_at = NOW()
+            WHERE suggestion_id = $3
+            "#
+        )
+        .bind(if was_edited { "edited" } else { "accepted" })
+        .bind(edited_text.as_deref())
+        .bind(suggestion_id)
+        .execute(&self.db_pool)
+        .await?;
+
         tracing::info!(
             "Suggestion accepted: id={}, inquiry={}, edited={}",
             suggestion_id,
@@ -449,6 +599,100 @@ Message Count: {}
         Ok(message_id)
     }
 
+    /// Reject a suggestion; the reason is given to the assistant with later suggestions
+    pub async fn reject_suggestion(
+        &self,
+        suggestion_id: Uuid,
+        user_id: Uuid,
+        reason: Option<String>,
+    ) -> Result<AssistantTurn> {
+        let suggestion = self.get_suggestion(suggestion_id, user_id).await?;
+        if suggestion.was_accepted {
+            return Err(AppError::BadRequest("Suggestion was already sent".to_string()));
+        }
+
+        let reason = reason.map(|r| r.trim().to_string()).filter(|r| !r.is_empty());
+        if reason.as_ref().map_or(false, |r| r.chars().count() > 1000) {
+            return Err(AppError::BadRequest("Rejection reason must be at most 1000 characters".to_string()));
+        }
+
+        let turn = sqlx::query_as::<_, AssistantTurn>(
+            r#"
+            UPDATE inquiry_assistant_turns
+            SET outcome = 'rejected', rejection_reason = $1, updated_at = NOW()
+            WHERE suggestion_id = $2 AND user_id = $3
+            RETURNING *
+            "#
+        )
+        .bind(&reason)
+        .bind(suggestion_id)
+        .bind(user_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Suggestion is not part of an assistant thread".to_string()))?;
+
+        tracing::info!(
+            "Suggestion rejected: id={}, inquiry={}, reason_given={}",
+            suggestion_id,
+            suggestion.inquiry_id,
+            reason.is_some()
+        );
+
+        Ok(turn)
+    }
+
+    /// The seller's assistant thread for an inquiry, oldest turn first
+    async fn load_thread(&self, inquiry_id: Uuid, user_id: Uuid) -> Result<Vec<AssistantTurn>> {
+        let turns = sqlx::query_as::<_, AssistantTurn>(
+            r#"
+            SELECT * FROM inquiry_assistant_turns
+            WHERE inquiry_id = $1 AND user_id = $2
+            ORDER BY created_at ASC
+            "#
+        )
+        .bind(inquiry_id)
+        .bind(user_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(turns)
+    }
+
+    /// Assistant thread with the part the next suggestion request would replay
+    pub async fn get_thread(&self, inquiry_id: Uuid, user_id: Uuid) -> Result<AssistantThreadResponse> {
+        let turns = self.load_thread(inquiry_id, user_id).await?;
+        let split = memory_split(&turns, MEMORY_TOKEN_BUDGET);
+
+        Ok(AssistantThreadResponse {
+            inquiry_id,
+            replayed_turns: turns.len() - split,
+            condensed_turns: split.min(MAX_CONDENSED_TURNS),
+            replayed_tokens: turns[split..].iter().map(|t| t.estimated_tokens).sum(),
+            token_budget: MEMORY_TOKEN_BUDGET,
+            turns,
+        })
+    }
+
+    /// Forget the assistant thread of an inquiry (suggestions themselves are kept)
+    pub async fn clear_thread(&self, inquiry_id: Uuid, user_id: Uuid) -> Result<u64> {
+        let result = sqlx::query(
+            "DELETE FROM inquiry_assistant_turns WHERE inquiry_id = $1 AND user_id = $2"
+        )
+        .bind(inquiry_id)
+        .bind(user_id)
+        .execute(&self.db_pool)
+        .await?;
+
+        tracing::info!(
+            "Assistant thread cleared: inquiry={}, user={}, turns={}",
+            inquiry_id,
+            user_id,
+            result.rows_affected()
+        );
+
+        Ok(result.rows_affected())
+    }
+
     /// Get suggestion by ID
     pub async fn get_suggestion(&self, suggestion_id: Uuid, user_id: Uuid) -> Result<InquiryAiSuggestion> {
         let suggestion = sqlx::query_as!(
@@ -513,3 +757,45 @@ Message Count: {}
         }
     }
 }
+
+/// Claude messages for a suggestion request: the newest turns that fit the memory budget
+/// as user/assistant pairs, each following user message opening with the seller's reaction
+/// to the previous suggestion, and older turns condensed into the final prompt
+fn build_memory_messages(turns: &[AssistantTurn], prompt: String) -> Vec<ClaudeMessage> {
+    let split = memory_split(turns, MEMORY_TOKEN_BUDGET);
+    let (condensed, replayed) = turns.split_at(split);
+    let mut messages = Vec::with_capacity(replayed.len() * 2 + 1);
+    let mut previous: Option<&AssistantTurn> = None;
+
+    for turn in replayed {
+        let mut request = String::new();
+        if let Some(prev) = previous {
+            request.push_str(&format!("SELLER FEEDBACK: {}\n\n", outcome_note(prev)));
+        }
+        request.push_str(&turn.request_text);
+
+        messages.push(user_message(request));
+        messages.push(assistant_message(
+            serde_json::json!({ "response": turn.response_text }).to_string()
+        ));
+        previous = Some(turn);
+    }
+
+    let mut final_prompt = String::new();
+    if !condensed.is_empty() {
+        let skip = condensed.len().saturating_sub(MAX_CONDENSED_TURNS);
+        final_prompt.push_str("EARLIER SUGGESTIONS FOR THIS INQUIRY (condensed):\n");
+        for turn in &condensed[skip..] {
+            final_prompt.push_str(&condense_turn(turn));
+            final_prompt.push('\n');
+        }
+        final_prompt.push('\n');
+    }
+    if let Some(prev) = previous {
+        final_prompt.push_str(&format!("SELLER FEEDBACK: {}\n\n", outcome_note(prev)));
+    }
+    final_prompt.push_str(&prompt);
+
+    messages.push(user_message(final_prompt));
+    messages
+}
@@ -1,10 +1,13 @@
 use uuid::Uuid;
+use std::sync::Arc;
 use crate::models::{
     inventory::{Inventory, CreateInventoryRequest, UpdateInventoryRequest, SearchInventoryRequest, InventoryResponse, ExpiryAlert},
+    search_engine::{FacetedSearchResponse, SearchIndex},
     user::UserResponse,
     pharmaceutical::PharmaceuticalResponse,
 };
 use crate::repositories::{InventoryRepository, PharmaceuticalRepository};
+use crate::services::{search_engine::{self, SearchEngine}, QueryCache, QueryCacheKind, SummaryAggregateService};
 use crate::middleware::error_handling::{Result, AppError};
 use chrono::NaiveDate;
 
@@ -31,6 +34,7 @@ impl InventoryService {
         }
 
         let inventory = self.inventory_repo.create(&request, user_id).await?;
+        QueryCache::invalidate(QueryCacheKind::MarketplaceSearch, "write");
         self.to_response(inventory).await
     }
 
@@ -58,24 +62,69 @@ impl InventoryService {
         Ok(responses)
     }
 
-    pub async fn search_marketplace(&self, request: SearchInventoryRequest) -> Result<Vec<InventoryResponse>> {
-        let results = self.inventory_repo.search_with_details(&request).await?;
-        
-        let mut responses = Vec::new();
-        for result in results {
-            responses.push(self.to_response_with_details(result).await?);
-        }
-
-        Ok(responses)
+    /// Text and faceted searches go to the search engine when one is configured, with
+    /// Postgres as the fallback. Results are cached briefly per distinct search;
+    /// inventory writes through this service flush them
+    pub async fn search_marketplace(
+        &self,
+        request: SearchInventoryRequest,
+        engine: Option<&Arc<dyn SearchEngine>>,
+    ) -> Result<FacetedSearchResponse<InventoryResponse>> {
+        let key = serde_json::to_string(&request).map_err(|e| AppError::Internal(e.into()))?;
+        let query = request.engine_query()?;
+
+        QueryCache::get_or_load(QueryCacheKind::MarketplaceSearch, &key, || async {
+            let hits = search_engine::search_with_fallback(engine, SearchIndex::Marketplace, query.as_ref()).await;
+
+            let (results, hits) = match (hits, engine) {
+                (Some(hits), Some(engine)) => {
+                    let ids: Vec<Uuid> = hits.keys.iter().filter_map(|key| key.parse().ok()).collect();
+                    let listings = self.inventory_repo.find_listed_with_details(&ids).await?;
+                    let listings = search_engine::in_hit_order(listings, &hits.keys, |l| l.inventory.id.to_string());
+                    (listings, Some((hits, engine.name())))
+                }
+                _ => (self.inventory_repo.search_with_details(&request).await?, None),
+            };
+
+            let mut responses = Vec::new();
+            for result in results {
+                responses.push(self.to_response_with_details(result).await?);
+            }
+
+            Ok(match hits {
+                Some((hits, engine_name)) => FacetedSearchResponse {
+                    results: responses,
+                    facets: hits.facets,
+                    facets_as_of: None,
+                    total: Some(hits.total),
+                    engine: engine_name.to_string(),
+                },
+                None => {
+                    let mut response = FacetedSearchResponse::from_postgres(responses);
+                    // Browsing the whole marketplace: facet counts from the summary instead
+                    // of a scan per request
+                    if let Some(query) = query.as_ref().filter(|q| q.text.is_empty() && q.filters.is_empty() && !q.facets.is_empty()) {
+                        let summary = SummaryAggregateService::new(self.inventory_repo.pool().clone())
+                            .marketplace_facets(&query.facets)
+                            .await?;
+                        response.facets = summary.value;
+                        response.facets_as_of = summary.refreshed_at;
+                    }
+                    response
+                }
+            })
+        }).await
     }
 
     pub async fn update_inventory(&self, inventory_id: Uuid, user_id: Uuid, request: UpdateInventoryRequest) -> Result<InventoryResponse> {
         let inventory = self.inventory_repo.update(inventory_id, user_id, &request).await?;
+        QueryCache::invalidate(QueryCacheKind::MarketplaceSearch, "write");
         self.to_response(inventory).await
     }
 
     pub async fn delete_inventory(&self, inventory_id: Uuid, user_id: Uuid) -> Result<()> {
         self.inventory_repo.delete(inventory_id, user_id).await?;
+        QueryCache::invalidate(QueryCacheKind::MarketplaceSearch, "write");
         Ok(())
     }
 
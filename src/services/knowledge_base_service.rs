@@ -0,0 +1,431 @@
+/// Regulatory knowledge-base management: CRUD with re-embedding, chunked bulk imports
+/// and per-source versioning. Every change bumps the version of the entry's regulation
+/// source and appends the entry state to `regulatory_knowledge_entry_history`.
+
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+use crate::middleware::error_handling::{Result, AppError};
+use crate::models::knowledge_base::*;
+use crate::services::ClaudeEmbeddingService;
+
+pub struct KnowledgeBaseService {
+    db_pool: PgPool,
+    embedding_service: ClaudeEmbeddingService,
+}
+
+const ENTRY_COLUMNS: &str = r#"
+    id, document_type, regulation_source, regulation_section, section_title, content,
+    metadata, source_version, created_by, updated_by, created_at, updated_at
+"#;
+
+impl KnowledgeBaseService {
+    /// `user_id` is charged for the embedding calls
+    pub fn new(db_pool: PgPool, api_key: String, user_id: Uuid) -> Result<Self> {
+        let embedding_service = ClaudeEmbeddingService::new(db_pool.clone(), api_key, user_id)?;
+        Ok(Self { db_pool, embedding_service })
+    }
+
+    pub async fn list_entries(&self, query: &ListKnowledgeEntriesQuery) -> Result<KnowledgeEntryListResponse> {
+        let page = query.page.unwrap_or(1).max(1);
+        let page_size = query.page_size.unwrap_or(50).clamp(1, 200);
+        let search = query.search.as_deref().map(|s| format!("%{}%", s.trim()));
+
+        let filter = r#"
+            WHERE ($1::text IS NULL OR document_type = $1)
+              AND ($2::text IS NULL OR regulation_source = $2)
+              AND ($3::text IS NULL OR section_title ILIKE $3 OR content ILIKE $3)
+        "#;
+
+        let total: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM regulatory_knowledge_base {}", filter))
+            .bind(&query.document_type)
+            .bind(&query.regulation_source)
+            .bind(&search)
+            .fetch_one(&self.db_pool)
+            .await?;
+
+        let entries = sqlx::query_as::<_, KnowledgeBaseEntry>(&format!(
+            "SELECT {} FROM regulatory_knowledge_base {} ORDER BY regulation_source, regulation_section, created_at LIMIT $4 OFFSET $5",
+            ENTRY_COLUMNS, filter
+        ))
+        .bind(&query.document_type)
+        .bind(&query.regulation_source)
+        .bind(&search)
+        .bind(page_size)
+        .bind((page - 1) * page_size)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(KnowledgeEntryListResponse { entries, total, page, page_size })
+    }
+
+    pub async fn get_entry(&self, entry_id: Uuid) -> Result<KnowledgeBaseEntry> {
+        sqlx::query_as::<_, KnowledgeBaseEntry>(&format!(
+            "SELECT {} FROM regulatory_knowledge_base WHERE id = $1",
+            ENTRY_COLUMNS
+        ))
+        .bind(entry_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Knowledge-base entry not found".to_string()))
+    }
+
+    pub async fn create_entry(&self, request: &CreateKnowledgeEntryRequest, user_id: Uuid) -> Result<KnowledgeBaseEntry> {
+        validate_document_type(&request.document_type).map_err(AppError::InvalidInput)?;
+        let source = request.regulation_source.trim();
+
+        let embedding = self.embedding_service.generate_embedding(&request.content).await?;
+
+        let mut tx = self.db_pool.begin().await?;
+        let version = Self::bump_source_version(&mut tx, source, "created", 1, None, user_id).await?;
+
+        let entry = sqlx::query_as::<_, KnowledgeBaseEntry>(&format!(
+            r#"
+            INSERT INTO regulatory_knowledge_base
+                (document_type, regulation_source, regulation_section, section_title, content,
+                 embedding, metadata, source_version, created_by, updated_by)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $9)
+            RETURNING {}
+            "#,
+            ENTRY_COLUMNS
+        ))
+        .bind(&request.document_type)
+        .bind(source)
+        .bind(&request.regulation_section)
+        .bind(request.section_title.trim())
+        .bind(&request.content)
+        .bind(embedding)
+        .bind(request.metadata.clone().unwrap_or_else(|| serde_json::json!({})))
+        .bind(version)
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        Self::record_history(&mut tx, &entry, "created", user_id).await?;
+        tx.commit().await?;
+
+        tracing::info!("Created knowledge entry {} ({} v{})", entry.id, source, version);
+
+        Ok(entry)
+    }
+
+    /// Update an entry, re-embedding it when its content changed
+    pub async fn update_entry(
+        &self,
+        entry_id: Uuid,
+        request: &UpdateKnowledgeEntryRequest,
+        user_id: Uuid,
+    ) -> Result<KnowledgeBaseEntry> {
+        if let Some(document_type) = &request.document_type {
+            validate_document_type(document_type).map_err(AppError::InvalidInput)?;
+        }
+
+        let existing = self.get_entry(entry_id).await?;
+        let source = existing.regulation_source.clone().unwrap_or_else(|| UNSPECIFIED_SOURCE.to_string());
+
+        let content_changed = request.content.as_ref().map_or(false, |c| *c != existing.content);
+        let embedding = match (&request.content, content_changed) {
+            (Some(content), true) => Some(self.embedding_service.generate_embedding(content).await?),
+            _ => None,
+        };
+
+        let mut tx = self.db_pool.begin().await?;
+        let version = Self::bump_source_version(&mut tx, &source, "updated", 1, None, user_id).await?;
+
+        let entry = sqlx::query_as::<_, KnowledgeBaseEntry>(&format!(
+            r#"
+            UPDATE regulatory_knowledge_base
+            SET
+                document_type = COALESCE($2, document_type),
+                regulation_section = COALESCE($3, regulation_section),
+                section_title = COALESCE($4, section_title),
+                content = COALESCE($5, content),
+                embedding = COALESCE($6, embedding),
+                metadata = COALESCE($7, metadata),
+                regulation_source = COALESCE(regulation_source, $8),
+                source_version = $9,
+                updated_by = $10,
+                updated_at = NOW()
+            WHERE id = $1
+            RETURNING {}
+            "#,
+            ENTRY_COLUMNS
+        ))
+        .bind(entry_id)
+        .bind(&request.document_type)
+        .bind(&request.regulation_section)
+        .bind(request.section_title.as_deref().map(str::trim))
+        .bind(&request.content)
+        .bind(embedding)
+        .bind(&request.metadata)
+        .bind(&source)
+        .bind(version)
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        Self::record_history(&mut tx, &entry, "updated", user_id).await?;
+        tx.commit().await?;
+
+        tracing::info!(
+            "Updated knowledge entry {} ({} v{}, re-embedded: {})",
+            entry_id, source, version, content_changed
+        );
+
+        Ok(entry)
+    }
+
+    pub async fn delete_entry(&self, entry_id: Uuid, user_id: Uuid) -> Result<()> {
+        let existing = self.get_entry(entry_id).await?;
+        let source = existing.regulation_source.clone().unwrap_or_else(|| UNSPECIFIED_SOURCE.to_string());
+
+        let mut tx = self.db_pool.begin().await?;
+        let version = Self::bump_source_version(&mut tx, &source, "deleted", 1, None, user_id).await?;
+
+        sqlx::query("DELETE FROM regulatory_knowledge_base WHERE id = $1")
+            .bind(entry_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let removed = KnowledgeBaseEntry { regulation_source: Some(source), source_version: version, ..existing };
+        Self::record_history(&mut tx, &removed, "deleted", user_id).await?;
+        tx.commit().await?;
+
+        tracing::info!("Deleted knowledge entry {}", entry_id);
+
+        Ok(())
+    }
+
+    /// Chunk an uploaded regulation into entries under one new source version
+    pub async fn import_document(
+        &self,
+        text: &str,
+        options: &KnowledgeImportOptions,
+        user_id: Uuid,
+    ) -> Result<KnowledgeImportResult> {
+        validate_document_type(&options.document_type).map_err(AppError::InvalidInput)?;
+        let source = options.regulation_source.trim();
+        if source.is_empty() || source.chars().count() > 200 {
+            return Err(AppError::InvalidInput(
+                "regulation_source is required (at most 200 characters)".to_string()
+            ));
+        }
+
+        let chunks = chunk_document(text, options.chunk_chars.unwrap_or(DEFAULT_CHUNK_CHARS));
+        if chunks.is_empty() {
+            return Err(AppError::InvalidInput("The uploaded document contains no text".to_string()));
+        }
+
+        // Embed before opening the transaction; this is the slow part
+        let embeddings = self.embedding_service
+            .generate_embeddings(chunks.iter().map(|c| c.content.clone()).collect())
+            .await?;
+        if embeddings.len() != chunks.len() {
+            return Err(AppError::Internal(anyhow::anyhow!(
+                "Expected {} embeddings, got {}",
+                chunks.len(),
+                embeddings.len()
+            )));
+        }
+
+        let mut tx = self.db_pool.begin().await?;
+        let version = Self::bump_source_version(
+            &mut tx,
+            source,
+            "imported",
+            chunks.len() as i32,
+            options.notes.as_deref(),
+            user_id,
+        ).await?;
+
+        let mut entries_replaced = 0;
+        if options.replace_existing {
+            let replaced = sqlx::query_as::<_, KnowledgeBaseEntry>(&format!(
+                "DELETE FROM regulatory_knowledge_base WHERE regulation_source = $1 RETURNING {}",
+                ENTRY_COLUMNS
+            ))
+            .bind(source)
+            .fetch_all(&mut *tx)
+            .await?;
+
+            for old in replaced {
+                let removed = KnowledgeBaseEntry { source_version: version, ..old };
+                Self::record_history(&mut tx, &removed, "deleted", user_id).await?;
+                entries_replaced += 1;
+            }
+        }
+
+        let total = chunks.len();
+        let mut entries = Vec::with_capacity(total);
+        for (index, (chunk, embedding)) in chunks.into_iter().zip(embeddings).enumerate() {
+            let section_title = chunk.heading.clone()
+                .unwrap_or_else(|| format!("{} (part {} of {})", source, index + 1, total));
+
+            let entry = sqlx::query_as::<_, KnowledgeBaseEntry>(&format!(
+                r#"
+                INSERT INTO regulatory_knowledge_base
+                    (document_type, regulation_source, regulation_section, section_title, content,
+                     embedding, metadata, source_version, created_by, updated_by)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $9)
+                RETURNING {}
+                "#,
+                ENTRY_COLUMNS
+            ))
+            .bind(&options.document_type)
+            .bind(source)
+            .bind(&options.regulation_section)
+            .bind(section_title.chars().take(500).collect::<String>())
+            .bind(&chunk.content)
+            .bind(embedding)
+            .bind(serde_json::json!({ "import_chunk": index + 1, "import_chunks": total }))
+            .bind(version)
+            .bind(user_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            Self::record_history(&mut tx, &entry, "imported", user_id).await?;
+            entries.push(entry);
+        }
+
+        tx.commit().await?;
+
+        tracing::info!(
+            "Imported {} knowledge entries into {} v{} ({} replaced)",
+            entries.len(), source, version, entries_replaced
+        );
+
+        Ok(KnowledgeImportResult {
+            regulation_source: source.to_string(),
+            source_version: version,
+            entries_created: entries.len(),
+            entries_replaced,
+            entries,
+        })
+    }
+
+    pub async fn list_sources(&self) -> Result<Vec<KnowledgeSource>> {
+        let sources = sqlx::query_as::<_, KnowledgeSource>(
+            r#"
+            SELECT s.regulation_source, s.current_version, s.updated_by, s.updated_at,
+                   COUNT(kb.id) AS entry_count
+            FROM regulatory_knowledge_sources s
+            LEFT JOIN regulatory_knowledge_base kb ON kb.regulation_source = s.regulation_source
+            GROUP BY s.regulation_source
+            ORDER BY s.regulation_source
+            "#
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(sources)
+    }
+
+    pub async fn list_source_versions(&self, regulation_source: &str) -> Result<Vec<KnowledgeSourceVersion>> {
+        let versions = sqlx::query_as::<_, KnowledgeSourceVersion>(
+            r#"
+            SELECT regulation_source, version, change_type, entries_changed, notes, created_by, created_at
+            FROM regulatory_knowledge_source_versions
+            WHERE regulation_source = $1
+            ORDER BY version DESC
+            "#
+        )
+        .bind(regulation_source)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        if versions.is_empty() {
+            return Err(AppError::NotFound(format!("Unknown regulation source '{}'", regulation_source)));
+        }
+
+        Ok(versions)
+    }
+
+    /// Change history of an entry, newest first (also available after deletion)
+    pub async fn entry_history(&self, entry_id: Uuid) -> Result<Vec<KnowledgeEntryVersion>> {
+        let history = sqlx::query_as::<_, KnowledgeEntryVersion>(
+            "SELECT * FROM regulatory_knowledge_entry_history WHERE entry_id = $1 ORDER BY id DESC"
+        )
+        .bind(entry_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        if history.is_empty() {
+            return Err(AppError::NotFound("Knowledge-base entry not found".to_string()));
+        }
+
+        Ok(history)
+    }
+
+    /// Next version of a regulation source, recorded with what changed
+    async fn bump_source_version(
+        tx: &mut Transaction<'_, Postgres>,
+        regulation_source: &str,
+        change_type: &str,
+        entries_changed: i32,
+        notes: Option<&str>,
+        user_id: Uuid,
+    ) -> Result<i32> {
+        let version: i32 = sqlx::query_scalar(
+            r#"
+            INSERT INTO regulatory_knowledge_sources (regulation_source, current_version, updated_by)
+            VALUES ($1, 1, $2)
+            ON CONFLICT (regulation_source) DO UPDATE SET
+                current_version = regulatory_knowledge_sources.current_version + 1,
+                updated_by = EXCLUDED.updated_by,
+                updated_at = NOW()
+            RETURNING current_version
+            "#
+        )
+        .bind(regulation_source)
+        .bind(user_id)
+        .fetch_one(&mut **tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO regulatory_knowledge_source_versions
+                (regulation_source, version, change_type, entries_changed, notes, created_by)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#
+        )
+        .bind(regulation_source)
+        .bind(version)
+        .bind(change_type)
+        .bind(entries_changed)
+        .bind(notes)
+        .bind(user_id)
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(version)
+    }
+
+    async fn record_history(
+        tx: &mut Transaction<'_, Postgres>,
+        entry: &KnowledgeBaseEntry,
+        change_type: &str,
+        user_id: Uuid,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO regulatory_knowledge_entry_history
+                (entry_id, regulation_source, source_version, change_type, document_type,
+                 regulation_section, section_title, content, metadata, changed_by)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            "#
+        )
+        .bind(entry.id)
+        .bind(entry.regulation_source.as_deref().unwrap_or(UNSPECIFIED_SOURCE))
+        .bind(entry.source_version)
+        .bind(change_type)
+        .bind(&entry.document_type)
+        .bind(&entry.regulation_section)
+        .bind(&entry.section_title)
+        .bind(&entry.content)
+        .bind(&entry.metadata)
+        .bind(user_id)
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,242 @@
+/// Legal Hold Service
+///
+/// Legal holds on a user (company), a set of transactions or all data of a category.
+/// Active holds exempt data from retention purges (see DataRetentionService) and block
+/// deleting a held user or a party to a held transaction. Holds are released, never
+/// deleted. Placing and releasing a hold, and every deletion a hold blocked, are
+/// written to the audit log.
+
+use crate::{
+    middleware::error_handling::{AppError, Result},
+    models::legal_hold::*,
+    services::comprehensive_audit_service::{
+        ActionResult, AuditLogEntry, ComprehensiveAuditService, EventCategory, Severity,
+    },
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub struct LegalHoldService {
+    db_pool: PgPool,
+    audit_service: ComprehensiveAuditService,
+}
+
+impl LegalHoldService {
+    pub fn new(db_pool: PgPool) -> Self {
+        let audit_service = ComprehensiveAuditService::new(db_pool.clone());
+        Self { db_pool, audit_service }
+    }
+
+    pub async fn list(&self, query: &LegalHoldQuery) -> Result<Vec<LegalHold>> {
+        let holds = sqlx::query_as::<_, LegalHold>(
+            r#"
+            SELECT * FROM data_legal_holds
+            WHERE ($1 OR released_at IS NULL)
+              AND ($2::UUID IS NULL OR user_id = $2)
+            ORDER BY created_at DESC
+            "#,
+        )
+        .bind(query.include_released)
+        .bind(query.user_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(holds)
+    }
+
+    pub async fn active_holds(&self) -> Result<Vec<LegalHold>> {
+        self.list(&LegalHoldQuery { include_released: false, user_id: None }).await
+    }
+
+    pub async fn get(&self, hold_id: Uuid) -> Result<LegalHold> {
+        sqlx::query_as::<_, LegalHold>("SELECT * FROM data_legal_holds WHERE id = $1")
+            .bind(hold_id)
+            .fetch_optional(&self.db_pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Legal hold not found".to_string()))
+    }
+
+    pub async fn create(&self, request: CreateLegalHoldRequest, admin_id: Uuid) -> Result<LegalHold> {
+        let target = request.target().map_err(AppError::BadRequest)?;
+
+        let subject_name = match request.user_id {
+            Some(user_id) => {
+                let company: Option<String> = sqlx::query_scalar("SELECT company_name FROM users WHERE id = $1")
+                    .bind(user_id)
+                    .fetch_optional(&self.db_pool)
+                    .await?;
+                Some(company.ok_or_else(|| AppError::NotFound("User not found".to_string()))?)
+            }
+            None => None,
+        };
+
+        let mut transaction_ids = request.transaction_ids.clone();
+        if let Some(ids) = transaction_ids.as_mut() {
+            ids.sort();
+            ids.dedup();
+            let found: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM transactions WHERE id = ANY($1)")
+                .bind(&*ids)
+                .fetch_one(&self.db_pool)
+                .await?;
+            if found != ids.len() as i64 {
+                return Err(AppError::NotFound(format!(
+                    "{} of the transactions were not found",
+                    ids.len() as i64 - found
+                )));
+            }
+        }
+
+        let hold = sqlx::query_as::<_, LegalHold>(
+            r#"
+            INSERT INTO data_legal_holds (
+                target_type, category, user_id, transaction_ids, subject_name, reason, reference, created_by
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING *
+            "#,
+        )
+        .bind(target.as_str())
+        .bind(request.category.map(|c| c.as_str()))
+        .bind(request.user_id)
+        .bind(&transaction_ids)
+        .bind(&subject_name)
+        .bind(request.reason.trim())
+        .bind(request.reference.as_deref().map(str::trim))
+        .bind(admin_id)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        self.audit_service.log(AuditLogEntry {
+            event_type: "legal_hold_placed".to_string(),
+            event_category: EventCategory::Admin,
+            severity: Severity::Warning,
+            actor_user_id: Some(admin_id),
+            actor_type: "user".to_string(),
+            resource_type: Some("legal_hold".to_string()),
+            resource_id: Some(hold.id.to_string()),
+            resource_name: hold.subject_name.clone(),
+            action: "place_legal_hold".to_string(),
+            action_result: ActionResult::Success,
+            event_data: serde_json::json!({
+                "target_type": hold.target_type,
+                "category": hold.category,
+                "user_id": hold.user_id,
+                "transaction_ids": hold.transaction_ids,
+                "reason": hold.reason,
+                "reference": hold.reference,
+            }),
+            compliance_tags: vec!["legal_hold".to_string(), "data_retention".to_string()],
+            ..Default::default()
+        }).await?;
+
+        tracing::info!(
+            "Audit: User {} placed legal hold {} on {} ({})",
+            admin_id,
+            hold.id,
+            hold.target_type,
+            hold.category.as_deref().unwrap_or("all categories")
+        );
+
+        Ok(hold)
+    }
+
+    pub async fn release(
+        &self,
+        hold_id: Uuid,
+        request: ReleaseLegalHoldRequest,
+        admin_id: Uuid,
+    ) -> Result<LegalHold> {
+        let hold = sqlx::query_as::<_, LegalHold>(
+            r#"
+            UPDATE data_legal_holds
+            SET released_at = NOW(), released_by = $2, release_reason = $3
+            WHERE id = $1 AND released_at IS NULL
+            RETURNING *
+            "#,
+        )
+        .bind(hold_id)
+        .bind(admin_id)
+        .bind(request.reason.trim())
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Active legal hold not found".to_string()))?;
+
+        self.audit_service.log(AuditLogEntry {
+            event_type: "legal_hold_released".to_string(),
+            event_category: EventCategory::Admin,
+            severity: Severity::Warning,
+            actor_user_id: Some(admin_id),
+            actor_type: "user".to_string(),
+            resource_type: Some("legal_hold".to_string()),
+            resource_id: Some(hold.id.to_string()),
+            resource_name: hold.subject_name.clone(),
+            action: "release_legal_hold".to_string(),
+            action_result: ActionResult::Success,
+            event_data: serde_json::json!({
+                "target_type": hold.target_type,
+                "user_id": hold.user_id,
+                "transaction_ids": hold.transaction_ids,
+                "release_reason": hold.release_reason,
+                "held_since": hold.created_at,
+            }),
+            compliance_tags: vec!["legal_hold".to_string(), "data_retention".to_string()],
+            ..Default::default()
+        }).await?;
+
+        tracing::info!("Audit: User {} released legal hold {}", admin_id, hold.id);
+
+        Ok(hold)
+    }
+
+    /// Fail with Forbidden, and record the blocked attempt, when the user is under an
+    /// active hold directly or as a party to a held transaction
+    pub async fn ensure_user_deletable(&self, user_id: Uuid, actor_id: Uuid, action: &str) -> Result<()> {
+        let hold_ids: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            SELECT h.id FROM data_legal_holds h
+            WHERE h.released_at IS NULL
+              AND (
+                  h.user_id = $1
+                  OR (h.target_type = 'transactions' AND EXISTS (
+                      SELECT 1 FROM transactions t
+                      WHERE t.id = ANY(h.transaction_ids) AND (t.seller_id = $1 OR t.buyer_id = $1)
+                  ))
+              )
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        if hold_ids.is_empty() {
+            return Ok(());
+        }
+
+        self.audit_service.log(AuditLogEntry {
+            event_type: "legal_hold_blocked".to_string(),
+            event_category: EventCategory::Security,
+            severity: Severity::Warning,
+            actor_user_id: Some(actor_id),
+            actor_type: "user".to_string(),
+            resource_type: Some("user".to_string()),
+            resource_id: Some(user_id.to_string()),
+            action: action.to_string(),
+            action_result: ActionResult::Failure,
+            event_data: serde_json::json!({ "legal_hold_ids": hold_ids }),
+            compliance_tags: vec!["legal_hold".to_string()],
+            ..Default::default()
+        }).await?;
+
+        tracing::warn!(
+            "Audit: User {} was blocked from {} of user {} by legal hold(s) {:?}",
+            actor_id,
+            action,
+            user_id,
+            hold_ids
+        );
+
+        Err(AppError::Forbidden(
+            "The user's data is under legal hold and cannot be deleted".to_string(),
+        ))
+    }
+}
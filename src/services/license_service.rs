@@ -0,0 +1,244 @@
+/// License Registry Service
+///
+/// Structured registry of the licenses a company trades under (wholesale distribution
+/// authorizations, GDP certificates, DEA registrations, ...), each with its scanned
+/// document. Uploads start `pending` until an admin verifies them.
+///
+/// Every review re-evaluates the owner's listing rights (see ListingRightsService).
+
+use chrono::Utc;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::{
+    middleware::error_handling::{AppError, Result},
+    models::{alerts::AlertPayload, license::*},
+    services::{ListingRightsService, NotificationService},
+    utils::file_storage::FileStorage,
+};
+
+pub struct LicenseService {
+    db_pool: PgPool,
+    storage: FileStorage,
+    listing_rights: ListingRightsService,
+}
+
+impl LicenseService {
+    pub fn new(db_pool: PgPool, file_storage_path: &str) -> Result<Self> {
+        let storage = FileStorage::new(std::path::Path::new(file_storage_path).join("licenses"))?;
+        let listing_rights = ListingRightsService::new(db_pool.clone());
+        Ok(Self { db_pool, storage, listing_rights })
+    }
+
+    /// Register a license with its document; it awaits admin verification
+    pub async fn upload(
+        &self,
+        user_id: Uuid,
+        request: CreateLicenseRequest,
+        filename: &str,
+        data: &[u8],
+    ) -> Result<BusinessLicense> {
+        let request = request.normalize(Utc::now().date_naive()).map_err(AppError::BadRequest)?;
+
+        if data.is_empty() {
+            return Err(AppError::BadRequest("The license document is empty".to_string()));
+        }
+        if data.len() > MAX_LICENSE_DOCUMENT_SIZE {
+            return Err(AppError::BadRequest(format!(
+                "License documents may be at most {} MB",
+                MAX_LICENSE_DOCUMENT_SIZE / 1024 / 1024
+            )));
+        }
+        let content_type = document_content_type(data).ok_or_else(|| {
+            AppError::BadRequest("License documents must be PDF, PNG or JPEG files".to_string())
+        })?;
+
+        let license_id = Uuid::new_v4();
+        let filename = crate::utils::log_sanitizer::sanitize_for_log(filename);
+        let (document_path, document_hash) = self.storage.save_file(license_id, &filename, data)?;
+
+        let result = sqlx::query_as::<_, BusinessLicense>(
+            r#"
+            INSERT INTO business_licenses (
+                id, user_id, license_type, license_number, issuing_authority, country_code,
+                issued_on, expires_on, document_path, document_filename, document_content_type,
+                document_size_bytes, document_hash
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            RETURNING *
+            "#
+        )
+        .bind(license_id)
+        .bind(user_id)
+        .bind(request.license_type.as_str())
+        .bind(&request.license_number)
+        .bind(&request.issuing_authority)
+        .bind(&request.country_code)
+        .bind(request.issued_on)
+        .bind(request.expires_on)
+        .bind(&document_path)
+        .bind(&filename)
+        .bind(content_type)
+        .bind(data.len() as i64)
+        .bind(&document_hash)
+        .fetch_one(&self.db_pool)
+        .await;
+
+        match result {
+            Ok(license) => Ok(license),
+            Err(e) => {
+                let _ = self.storage.delete_file(&document_path);
+                match e {
+                    sqlx::Error::Database(db_err) if db_err.code().as_deref() == Some("23505") => Err(AppError::Conflict),
+                    other => Err(other.into()),
+                }
+            }
+        }
+    }
+
+    /// The user's licenses and listing rights
+    pub async fn registry(&self, user_id: Uuid) -> Result<LicenseRegistryResponse> {
+        let (listing_suspended_at, listing_suspension_reason) = sqlx::query_as::<_, (Option<chrono::DateTime<Utc>>, Option<String>)>(
+            "SELECT listing_suspended_at, listing_suspension_reason FROM users WHERE id = $1"
+        )
+        .bind(user_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+        Ok(LicenseRegistryResponse {
+            listing_suspended: listing_suspended_at.is_some(),
+            listing_suspended_at,
+            listing_suspension_reason,
+            licenses: self.listing_rights.licenses_of(user_id).await?,
+        })
+    }
+
+    /// A license, to its owner or an admin
+    pub async fn get(&self, license_id: Uuid, user_id: Uuid, is_admin: bool) -> Result<BusinessLicense> {
+        let license = sqlx::query_as::<_, BusinessLicense>("SELECT * FROM business_licenses WHERE id = $1")
+            .bind(license_id)
+            .fetch_optional(&self.db_pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("License not found".to_string()))?;
+
+        if license.user_id != user_id && !is_admin {
+            return Err(AppError::NotFound("License not found".to_string()));
+        }
+
+        Ok(license)
+    }
+
+    pub async fn document(&self, license_id: Uuid, user_id: Uuid, is_admin: bool) -> Result<(BusinessLicense, Vec<u8>)> {
+        let license = self.get(license_id, user_id, is_admin).await?;
+        let data = self.storage.read_file(&license.document_path)?;
+        Ok((license, data))
+    }
+
+    /// Remove a license that is still pending or was rejected (uploaded in error);
+    /// verified licenses stay on record and are revoked instead
+    pub async fn delete(&self, license_id: Uuid, user_id: Uuid) -> Result<()> {
+        let license = self.get(license_id, user_id, false).await?;
+        if license.status != LicenseStatus::Pending.as_str() && license.status != LicenseStatus::Rejected.as_str() {
+            return Err(AppError::BadRequest("Only pending or rejected licenses can be deleted".to_string()));
+        }
+
+        sqlx::query("DELETE FROM business_licenses WHERE id = $1")
+            .bind(license_id)
+            .execute(&self.db_pool)
+            .await?;
+        self.storage.delete_file(&license.document_path)?;
+
+        Ok(())
+    }
+
+    // ========================================================================
+    // ADMIN
+    // ========================================================================
+
+    pub async fn list(&self, query: &LicenseQuery) -> Result<Vec<BusinessLicense>> {
+        let limit = query.limit.unwrap_or(50).clamp(1, 200);
+        let offset = query.offset.unwrap_or(0).max(0);
+
+        let licenses = sqlx::query_as::<_, BusinessLicense>(
+            r#"
+            SELECT * FROM business_licenses
+            WHERE ($1::TEXT IS NULL OR status = $1)
+              AND ($2::TEXT IS NULL OR license_type = $2)
+              AND ($3::UUID IS NULL OR user_id = $3)
+              AND ($4::INT IS NULL OR (status = 'verified' AND expires_on <= CURRENT_DATE + $4))
+            ORDER BY
+                CASE WHEN $4::INT IS NULL THEN created_at END DESC,
+                expires_on ASC
+            LIMIT $5 OFFSET $6
+            "#
+        )
+        .bind(query.status.map(|s| s.as_str()))
+        .bind(query.license_type.map(|t| t.as_str()))
+        .bind(query.user_id)
+        .bind(query.expiring_within_days.map(|days| days.max(0)))
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(licenses)
+    }
+
+    /// Verify or reject a pending license, or revoke a verified one, then re-evaluate
+    /// the owner's listing rights
+    pub async fn review(&self, license_id: Uuid, admin_id: Uuid, request: ReviewLicenseRequest) -> Result<BusinessLicense> {
+        let notes = request.notes.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+        if request.decision != LicenseReviewDecision::Verify && notes.is_none() {
+            return Err(AppError::BadRequest("Notes are required when rejecting or revoking a license".to_string()));
+        }
+
+        let current = self.get(license_id, admin_id, true).await?;
+        if !request.decision.applies_to(&current.status) {
+            return Err(AppError::BadRequest(format!("The license is {}", current.status)));
+        }
+        if request.decision == LicenseReviewDecision::Verify
+            && current.is_expired(Utc::now().date_naive())
+        {
+            return Err(AppError::BadRequest("An expired license can't be verified".to_string()));
+        }
+
+        let status = request.decision.resulting_status();
+        let license = sqlx::query_as::<_, BusinessLicense>(
+            r#"
+            UPDATE business_licenses
+            SET status = $2, reviewed_by = $3, reviewed_at = NOW(), review_notes = $4, updated_at = NOW()
+            WHERE id = $1 AND status = $5
+            RETURNING *
+            "#
+        )
+        .bind(license_id)
+        .bind(status.as_str())
+        .bind(admin_id)
+        .bind(&notes)
+        .bind(&current.status)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or(AppError::Conflict)?;
+
+        self.notify(AlertPayload::new_license_reviewed(
+            license.user_id,
+            license.id,
+            license.type_label(),
+            &license.license_number,
+            status.as_str(),
+            notes.as_deref(),
+        ))
+        .await;
+
+        self.listing_rights.refresh(license.user_id).await?;
+        Ok(license)
+    }
+
+    /// Notifications are best effort; the registry change is already stored
+    async fn notify(&self, payload: AlertPayload) {
+        let user_id = payload.user_id;
+        if let Err(e) = NotificationService::new(self.db_pool.clone()).create_alert(payload).await {
+            tracing::warn!("Could not notify user {} about a license: {:?}", user_id, e);
+        }
+    }
+}
@@ -0,0 +1,197 @@
+/// Listing Rights Service
+///
+/// A company's listing rights follow its verified licenses: once every verified license
+/// of a type has expired, its listings are hidden from the marketplace and it can't list
+/// inventory until a renewed license is verified. Rights are re-evaluated on every
+/// license review and by the daily license expiry check, which also sends reminders 60,
+/// 30 and 7 days before a verified license expires.
+
+use chrono::Utc;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::{
+    middleware::error_handling::{AppError, Result},
+    models::{alerts::AlertPayload, license::*},
+    services::NotificationService,
+};
+
+pub struct ListingRightsService {
+    db_pool: PgPool,
+}
+
+impl ListingRightsService {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self { db_pool }
+    }
+
+    /// Why the user's listing rights are suspended, or None while they may list
+    pub async fn suspension(&self, user_id: Uuid) -> Result<Option<String>> {
+        let reason: Option<Option<String>> = sqlx::query_scalar(
+            "SELECT listing_suspension_reason FROM users WHERE id = $1 AND listing_suspended_at IS NOT NULL"
+        )
+        .bind(user_id)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        Ok(reason.map(|reason| reason.unwrap_or_else(|| "expired license".to_string())))
+    }
+
+    /// Reject listing changes of a company whose listing rights are suspended
+    pub async fn ensure_can_list(&self, user_id: Uuid) -> Result<()> {
+        match self.suspension(user_id).await? {
+            Some(reason) => Err(AppError::Forbidden(format!(
+                "Listing rights are suspended ({}). Upload a renewed license to restore them.",
+                reason
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Suspend or restore the user's listing rights according to their verified
+    /// licenses, notifying them of a change. Returns whether the rights changed.
+    pub async fn refresh(&self, user_id: Uuid) -> Result<bool> {
+        let licenses = self.licenses_of(user_id).await?;
+        let reason = listing_suspension_reason(&licenses, Utc::now().date_naive());
+
+        // Only flips are reported back, so the notification goes out once
+        let changed: Option<bool> = sqlx::query_scalar(
+            r#"
+            UPDATE users
+            SET listing_suspended_at = CASE WHEN $2::TEXT IS NULL THEN NULL ELSE COALESCE(listing_suspended_at, NOW()) END,
+                listing_suspension_reason = $2
+            WHERE id = $1
+              AND (listing_suspended_at IS NULL) <> ($2::TEXT IS NULL)
+            RETURNING TRUE
+            "#
+        )
+        .bind(user_id)
+        .bind(&reason)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        if changed.is_some() {
+            match &reason {
+                Some(reason) => tracing::warn!("Listing rights of user {} suspended: {}", user_id, reason),
+                None => tracing::info!("Listing rights of user {} restored", user_id),
+            }
+            self.notify(AlertPayload::new_listing_rights_changed(user_id, reason.as_deref())).await;
+        } else if let Some(reason) = &reason {
+            // Keep the reason current while suspended (e.g. a second license lapsed)
+            sqlx::query("UPDATE users SET listing_suspension_reason = $2 WHERE id = $1 AND listing_suspended_at IS NOT NULL")
+                .bind(user_id)
+                .bind(reason)
+                .execute(&self.db_pool)
+                .await?;
+        }
+
+        Ok(changed.is_some())
+    }
+
+    /// Daily check: expiry reminders for verified licenses, then listing rights of
+    /// every company with an expired verified license or a current suspension.
+    /// Returns the number of notifications sent.
+    pub async fn run_expiry_check(&self) -> Result<i32> {
+        let today = Utc::now().date_naive();
+        let max_days = LICENSE_REMINDER_DAYS.iter().copied().max().unwrap_or(0);
+        let mut notifications = 0;
+
+        let expiring = sqlx::query_as::<_, BusinessLicense>(
+            r#"
+            SELECT * FROM business_licenses
+            WHERE status = 'verified'
+              AND expires_on >= CURRENT_DATE
+              AND expires_on <= CURRENT_DATE + $1
+            "#
+        )
+        .bind(max_days)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        for license in expiring {
+            let Some(days_to_expiry) = license.days_to_expiry(today) else { continue };
+            // A renewal of the same type that outlives this license makes the reminder moot
+            if self.has_renewal(&license).await? {
+                continue;
+            }
+            let Some(threshold) = reminder_due(days_to_expiry, license.last_reminder_days) else { continue };
+
+            sqlx::query("UPDATE business_licenses SET last_reminder_days = $2 WHERE id = $1")
+                .bind(license.id)
+                .bind(threshold)
+                .execute(&self.db_pool)
+                .await?;
+
+            self.notify(AlertPayload::new_license_expiring(
+                license.user_id,
+                license.id,
+                license.type_label(),
+                &license.license_number,
+                license.expires_on.unwrap_or(today),
+                days_to_expiry,
+            ))
+            .await;
+            notifications += 1;
+        }
+
+        let user_ids: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            SELECT DISTINCT user_id FROM business_licenses
+            WHERE status = 'verified' AND expires_on <= CURRENT_DATE
+            UNION
+            SELECT id FROM users WHERE listing_suspended_at IS NOT NULL
+            "#
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        for user_id in user_ids {
+            match self.refresh(user_id).await {
+                Ok(true) => notifications += 1,
+                Ok(false) => {}
+                Err(e) => tracing::error!("Could not refresh listing rights of user {}: {}", user_id, e),
+            }
+        }
+
+        Ok(notifications)
+    }
+
+    async fn has_renewal(&self, license: &BusinessLicense) -> Result<bool> {
+        let renewed: bool = sqlx::query_scalar(
+            r#"
+            SELECT EXISTS(
+                SELECT 1 FROM business_licenses
+                WHERE user_id = $1 AND license_type = $2 AND id <> $3
+                  AND status = 'verified'
+                  AND (expires_on IS NULL OR expires_on > $4)
+            )
+            "#
+        )
+        .bind(license.user_id)
+        .bind(&license.license_type)
+        .bind(license.id)
+        .bind(license.expires_on)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        Ok(renewed)
+    }
+
+    pub async fn licenses_of(&self, user_id: Uuid) -> Result<Vec<BusinessLicense>> {
+        let licenses = sqlx::query_as::<_, BusinessLicense>(
+            "SELECT * FROM business_licenses WHERE user_id = $1 ORDER BY license_type, expires_on DESC NULLS FIRST, created_at DESC"
+        )
+        .bind(user_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(licenses)
+    }
+
+    /// Notifications are best effort; the change is already stored
+    async fn notify(&self, payload: AlertPayload) {
+        let user_id = payload.user_id;
+        if let Err(e) = NotificationService::new(self.db_pool.clone()).create_alert(payload).await {
+            tracing::warn!("Could not notify user {} about their listing rights: {:?}", user_id, e);
+        }
+    }
+}
@@ -0,0 +1,119 @@
+/// Maintenance Service
+///
+/// Maintenance mode is a runtime setting (`maintenance.enabled`, defaulting to the
+/// MAINTENANCE_MODE environment variable), so switching it reaches every instance
+/// within moments. While it is on:
+/// - non-admin API traffic gets 503 with Retry-After (see `maintenance_middleware`)
+/// - schedulers skip their runs and the job worker claims no new jobs; work already
+///   running finishes normally
+///
+/// `status` reports how much work is still running across all instances, so
+/// migrations can start once the platform is drained.
+
+use serde_json::Value;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::{
+    middleware::error_handling::{AppError, Result},
+    models::{maintenance::*, settings::*},
+    services::{RuntimeSettings, SettingsService, Shutdown},
+};
+
+pub struct MaintenanceService {
+    db_pool: PgPool,
+}
+
+impl MaintenanceService {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self { db_pool }
+    }
+
+    /// Whether a scheduled run should be skipped for maintenance, or because the instance
+    /// is shutting down
+    pub fn pauses(scheduler: &str) -> bool {
+        if Shutdown::requested() {
+            tracing::debug!("Shutting down: skipping scheduled {} run", scheduler);
+            return true;
+        }
+
+        let paused = RuntimeSettings::maintenance_enabled();
+        if paused {
+            tracing::debug!("Maintenance mode: skipping scheduled {} run", scheduler);
+        }
+        paused
+    }
+
+    pub async fn status(&self) -> Result<MaintenanceStatus> {
+        let enabled = RuntimeSettings::maintenance_enabled();
+        let switch: Option<(Option<Uuid>, chrono::DateTime<chrono::Utc>)> = sqlx::query_as(
+            "SELECT updated_by, updated_at FROM settings WHERE key = $1"
+        )
+        .bind(MAINTENANCE_ENABLED)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        Ok(MaintenanceStatus {
+            enabled,
+            changed_at: switch.map(|(_, at)| at),
+            changed_by: switch.and_then(|(by, _)| by),
+            retry_after_seconds: RuntimeSettings::maintenance_retry_after_seconds(),
+            message: RuntimeSettings::maintenance_message(),
+            drain: self.drain_status(enabled).await?,
+        })
+    }
+
+    async fn drain_status(&self, enabled: bool) -> Result<DrainStatus> {
+        let jobs = sqlx::query_as::<_, RunningJobCount>(
+            r#"
+            SELECT job_type, COUNT(*) AS running
+            FROM background_jobs
+            WHERE status = 'running'
+            GROUP BY job_type
+            ORDER BY job_type
+            "#
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let alert_jobs: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM alert_scheduler_jobs WHERE lease_expires_at > NOW()"
+        )
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        let erp_syncs: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM erp_connections WHERE last_sync_status = 'running'"
+        )
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        Ok(DrainStatus::new(enabled, jobs, alert_jobs, erp_syncs))
+    }
+
+    /// Switch maintenance on or off (and optionally its Retry-After and message)
+    pub async fn update(&self, request: &UpdateMaintenanceRequest, admin_id: Uuid) -> Result<MaintenanceStatus> {
+        if let Some(message) = &request.message {
+            if message.trim().is_empty() {
+                return Err(AppError::BadRequest("message must not be empty".to_string()));
+            }
+        }
+
+        let settings = SettingsService::new(self.db_pool.clone());
+        if let Some(retry_after) = request.retry_after_seconds {
+            settings.update(MAINTENANCE_RETRY_AFTER_SECONDS, &Value::from(retry_after), admin_id).await?;
+        }
+        if let Some(message) = &request.message {
+            settings.update(MAINTENANCE_MESSAGE, &Value::from(message.as_str()), admin_id).await?;
+        }
+        // Last, so rejected requests already carry the new Retry-After and message
+        settings.update(MAINTENANCE_ENABLED, &Value::Bool(request.enabled), admin_id).await?;
+
+        if request.enabled {
+            tracing::warn!("🚧 Maintenance mode enabled by {}", admin_id);
+        } else {
+            tracing::info!("✅ Maintenance mode disabled by {}", admin_id);
+        }
+
+        self.status().await
+    }
+}
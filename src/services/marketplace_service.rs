@@ -2,6 +2,7 @@ use uuid::Uuid;
 use crate::models::{
     marketplace::{Inquiry, CreateInquiryRequest, UpdateInquiryRequest, CreateTransactionRequest, TransactionResponse, InquiryResponse},
     inventory::InventoryResponse,
+    regulatory_rule::EvaluationContext,
 };
 use crate::repositories::{MarketplaceRepository, InventoryRepository, UserRepository, PharmaceuticalRepository};
 use crate::services::InventoryService;
@@ -42,6 +43,11 @@ impl MarketplaceService {
             return Err(AppError::InvalidInput("Cannot inquire about your own inventory".to_string()));
         }
 
+        // Hidden listings (taken down, suspended seller) can't be inquired about
+        if !self.inventory_repo.is_listed(inventory.id).await? {
+            return Err(AppError::NotFound("Resource not found".to_string()));
+        }
+
         if self.marketplace_repo.inquiry_exists_for_buyer(request.inventory_id, buyer_id).await? {
             return Err(AppError::Conflict);
         }
@@ -126,6 +132,7 @@ impl MarketplaceService {
             inventory: inventory_response,
             buyer,
             seller,
+            regulatory_requirements: None,
         })
     }
 
@@ -271,4 +278,98 @@ impl MarketplaceService {
 
         Ok(updated_transaction.into())
     }
-}
\ No newline at end of file
+}
+
+/// Create an inquiry for `buyer_id` the way the marketplace API does: the seller's
+/// listing rights and the jurisdiction rules are enforced, the evaluation is recorded and
+/// the seller is notified (in-app and through their webhooks)
+pub async fn place_inquiry(
+    db_pool: &sqlx::PgPool,
+    encryption_key: &str,
+    request: CreateInquiryRequest,
+    buyer_id: Uuid,
+) -> Result<InquiryResponse> {
+    let inventory_repo = InventoryRepository::new(db_pool.clone());
+    let user_repo = UserRepository::new(db_pool.clone(), encryption_key)?;
+
+    // Get inventory to find seller and product name
+    let inventory = inventory_repo
+        .find_by_id(request.inventory_id)
+        .await?
+        .ok_or(AppError::NotFound("Inventory not found".to_string()))?;
+
+    let seller_id = inventory.user_id;
+
+    // Listings of sellers with suspended listing rights (expired licenses) are hidden
+    let listing_rights = crate::services::ListingRightsService::new(db_pool.clone());
+    if listing_rights.suspension(seller_id).await?.is_some() {
+        return Err(AppError::NotFound("Inventory not found".to_string()));
+    }
+
+    // Get buyer company name
+    let buyer = user_repo.find_by_id(buyer_id).await?
+        .ok_or(AppError::NotFound("User not found".to_string()))?;
+
+    // Get product name from pharmaceuticals
+    let pharma_repo = PharmaceuticalRepository::new(db_pool.clone());
+    let pharma = pharma_repo.find_by_id(inventory.pharmaceutical_id).await?
+        .ok_or(AppError::NotFound("Product not found".to_string()))?;
+
+    let marketplace_service = MarketplaceService::new(
+        MarketplaceRepository::new(db_pool.clone()),
+        inventory_repo,
+        user_repo,
+        pharma_repo,
+        InventoryService::new(
+            InventoryRepository::new(db_pool.clone()),
+            PharmaceuticalRepository::new(db_pool.clone()),
+        ),
+    );
+
+    // Jurisdiction rules (authorization, licenses, permits); refuses legally blocked trades
+    let regulatory_rules = crate::services::RegulatoryRulesService::new(db_pool.clone());
+    let evaluation = regulatory_rules
+        .enforce(EvaluationContext::Inquiry, request.inventory_id, buyer_id)
+        .await?;
+
+    let mut inquiry = marketplace_service.create_inquiry(request.clone(), buyer_id).await?;
+
+    if let Err(e) = regulatory_rules.record(EvaluationContext::Inquiry, &evaluation, Some(inquiry.id), None).await {
+        tracing::warn!("Failed to record regulatory evaluation of inquiry {}: {}", inquiry.id, e);
+    }
+    inquiry.regulatory_requirements = Some(evaluation.requirements);
+
+    // Create notification for seller
+    let notification_service = crate::services::NotificationService::new(db_pool.clone());
+    let product_name = format!("{} {}", pharma.brand_name, pharma.generic_name);
+    let alert_payload = crate::models::alerts::AlertPayload::new_inquiry(
+        seller_id,
+        buyer_id,
+        &buyer.company_name,
+        &product_name,
+        request.quantity_requested,
+        inquiry.id,
+        request.inventory_id,
+    );
+
+    // Fire and forget - don't fail inquiry creation if notification fails
+    if let Err(e) = notification_service.create_alert(alert_payload).await {
+        tracing::warn!("Failed to create inquiry notification: {}", e);
+    }
+
+    crate::services::OutboundWebhookService::emit_in_background(
+        db_pool.clone(),
+        Some(seller_id),
+        crate::models::webhook::WebhookEventType::InquiryCreated,
+        serde_json::json!({
+            "inquiry_id": inquiry.id,
+            "inventory_id": request.inventory_id,
+            "buyer_company": buyer.company_name,
+            "product_name": product_name,
+            "quantity_requested": request.quantity_requested,
+            "message": request.message,
+        }),
+    );
+
+    Ok(inquiry)
+}
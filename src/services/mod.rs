@@ -6,17 +6,23 @@ pub mod marketplace_service;
 pub mod openfda_service;
 pub mod ema_service;
 pub mod claude_ai_service;
+pub mod ai_response_cache_service;
+pub mod ai_output_validation;
 pub mod file_parser_service;
 pub mod ai_import_service;
+pub mod document_extraction_service;
 pub mod inventory_validator_service;
 pub mod batch_import_processor;
 pub mod audit_service;
 pub mod nl_query_service;
+pub mod nl_report_service;
+pub mod email_service;
 pub mod inquiry_assistant_service;
 pub mod notification_service;
 pub mod alert_scheduler_service;
 pub mod encryption_service;
 pub mod encryption_key_rotation_service;
+pub mod encryption_reencryption_service;
 pub mod api_quota_service;
 pub mod token_blacklist_service;
 pub mod comprehensive_audit_service;
@@ -26,7 +32,61 @@ pub mod claude_embedding_service;
 pub mod regulatory_document_generator;
 pub mod webhook_security_service;
 pub mod oauth_service;
+pub mod product_image_service;
+pub mod catalog_subscription_service;
+pub mod openfda_device_service;
+pub mod openfda_recall_service;
+pub mod ema_document_service;
+pub mod sync_anomaly_service;
+pub mod sync_source_service;
+pub mod catalog_export_service;
+pub mod federated_search_service;
+pub mod semantic_search_service;
+pub mod pricing_suggestion_service;
+pub mod ai_quota_service;
+pub mod knowledge_base_service;
+pub mod anomaly_detection_service;
+pub mod outbound_webhook_service;
+pub mod alert_channel_service;
+pub mod realtime_service;
+pub mod announcement_service;
+pub mod dscsa_service;
+pub mod product_verification_service;
+pub mod electronic_signature_service;
+pub mod data_retention_service;
+pub mod legal_hold_service;
+pub mod document_version_service;
+pub mod temperature_excursion_service;
+pub mod license_service;
+pub mod listing_rights_service;
+pub mod sanctions_list_service;
+pub mod sanctions_screening_service;
+pub mod regulatory_rules_service;
+pub mod audit_chain_service;
+pub mod document_batch_service;
+pub mod health_service;
+pub mod background_job_service;
+pub mod background_job_worker;
+pub mod ops_dashboard_service;
+pub mod usage_metering_service;
+pub mod user_suspension_service;
+pub mod settings_service;
+pub mod admin_analytics_service;
+pub mod maintenance_service;
+pub mod shutdown_service;
+pub mod payload_capture_service;
+pub mod verification_service;
+pub mod query_cache_service;
+pub mod data_export_service;
+pub mod summary_aggregate_service;
+pub mod scheduler_leader_service;
+pub mod fhir_service;
+pub mod batch_operation_service;
+pub mod regulator_catalogs;
+pub mod search_engine;
 pub mod erp;
+pub mod edi;
+pub mod accounting;
 
 pub use admin_service::*;
 pub use auth_service::*;
@@ -36,17 +96,23 @@ pub use marketplace_service::*;
 pub use openfda_service::*;
 pub use ema_service::*;
 pub use claude_ai_service::*;
+pub use ai_response_cache_service::*;
+pub use ai_output_validation::*;
 pub use file_parser_service::*;
 pub use ai_import_service::*;
+pub use document_extraction_service::*;
 pub use inventory_validator_service::*;
 pub use batch_import_processor::*;
 pub use audit_service::*;
 pub use nl_query_service::*;
+pub use nl_report_service::*;
+pub use email_service::*;
 pub use inquiry_assistant_service::*;
 pub use notification_service::*;
 pub use alert_scheduler_service::*;
 pub use encryption_service::*;
 pub use encryption_key_rotation_service::*;
+pub use encryption_reencryption_service::*;
 pub use api_quota_service::*;
 pub use token_blacklist_service::*;
 pub use comprehensive_audit_service::*;
@@ -55,4 +121,54 @@ pub use ed25519_signature_service::*;
 pub use claude_embedding_service::*;
 pub use regulatory_document_generator::*;
 pub use webhook_security_service::*;
-pub use oauth_service::*;
\ No newline at end of file
+pub use oauth_service::*;
+pub use product_image_service::*;
+pub use catalog_subscription_service::*;
+pub use openfda_device_service::*;
+pub use openfda_recall_service::*;
+pub use ema_document_service::*;
+pub use sync_anomaly_service::*;
+pub use sync_source_service::*;
+pub use catalog_export_service::*;
+pub use federated_search_service::*;
+pub use semantic_search_service::*;
+pub use pricing_suggestion_service::*;
+pub use ai_quota_service::*;
+pub use knowledge_base_service::*;
+pub use anomaly_detection_service::*;
+pub use outbound_webhook_service::*;
+pub use alert_channel_service::*;
+pub use realtime_service::*;
+pub use announcement_service::*;
+pub use dscsa_service::*;
+pub use product_verification_service::*;
+pub use electronic_signature_service::*;
+pub use data_retention_service::*;
+pub use legal_hold_service::*;
+pub use document_version_service::*;
+pub use temperature_excursion_service::*;
+pub use license_service::*;
+pub use listing_rights_service::*;
+pub use sanctions_list_service::*;
+pub use sanctions_screening_service::*;
+pub use regulatory_rules_service::*;
+pub use audit_chain_service::*;
+pub use document_batch_service::*;
+pub use health_service::*;
+pub use background_job_service::*;
+pub use background_job_worker::*;
+pub use ops_dashboard_service::*;
+pub use usage_metering_service::*;
+pub use user_suspension_service::*;
+pub use settings_service::*;
+pub use admin_analytics_service::*;
+pub use maintenance_service::*;
+pub use shutdown_service::*;
+pub use payload_capture_service::*;
+pub use verification_service::*;
+pub use query_cache_service::*;
+pub use data_export_service::*;
+pub use summary_aggregate_service::*;
+pub use scheduler_leader_service::*;
+pub use fhir_service::*;
+pub use batch_operation_service::*;
\ No newline at end of file
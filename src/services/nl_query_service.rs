@@ -7,6 +7,7 @@ use crate::{
     middleware::error_handling::{Result, AppError},
     models::nl_query::*,
     services::claude_ai_service::{ClaudeAIService, ClaudeRequestConfig, user_message},
+    services::ai_response_cache_service::AiFeature,
 };
 use sqlx::{PgPool, Row, Column};
 use uuid::Uuid;
@@ -22,7 +23,8 @@ const DATABASE_SCHEMA: &str = r#"
 
 ### pharmaceuticals
 Columns: id (UUID), brand_name (TEXT), generic_name (TEXT), ndc_code (TEXT), manufacturer (TEXT),
-         category (TEXT), strength (TEXT), dosage_form (TEXT), storage_requirements (TEXT)
+         category (TEXT), strength (TEXT), dosage_form (TEXT), storage_requirements (TEXT),
+         product_domain (TEXT: 'human_drug', 'veterinary', 'medical_device'), udi_di (TEXT), device_class (TEXT)
 Indexes: brand_name, generic_name, ndc_code, manufacturer, category
 
 ### inventory
@@ -112,7 +114,7 @@ Response format:
 
 DATABASE SCHEMA:
 - inventory: user_id, pharmaceutical_id, batch_number, quantity, expiry_date, unit_price, storage_location, status ('available', 'reserved', 'sold', 'expired')
-- pharmaceuticals: id, brand_name, generic_name, ndc_code, manufacturer, category, strength, dosage_form, storage_requirements
+- pharmaceuticals: id, brand_name, generic_name, ndc_code, manufacturer, category, strength, dosage_form, storage_requirements, product_domain ('human_drug', 'veterinary', 'medical_device'), udi_di, device_class
 - users: id, company_name, company_type, is_verified
 - inquiries: id, inventory_id, buyer_id, quantity_requested, message, status
 - transactions: id, seller_id, buyer_id, quantity, unit_price, total_price, transaction_date, status
@@ -211,13 +213,15 @@ impl NlQueryService {
             system_prompt: Some(SYSTEM_PROMPT.to_string()),
         };
 
-        let claude_response = match self.claude_service.send_message(
+        // 4. Parse and validate AI response (one repair retry on schema drift)
+        let (ai_response, claude_response) = match self.claude_service.send_message_validated::<AiAssistantResponse>(
             vec![user_message(prompt)],
             config,
             user_id,
             Some(session_id),
+            AiFeature::NlQuery,
         ).await {
-            Ok(response) => response,
+            Ok(validated) => (validated.value, validated.response),
             Err(e) => {
                 self.mark_session_failed(
                     session_id,
@@ -227,36 +231,7 @@ impl NlQueryService {
                 return Err(e);
             }
         };
-
-        // 4. Parse AI response (strip markdown code fences if present)
-        let content = claude_response.content.trim();
-        let json_content = if content.starts_with("```json") {
-            content.trim_start_matches("```json")
-                   .trim_start_matches("```")
-                   .trim_end_matches("```")
-                   .trim()
-        } else if content.starts_with("```") {
-            content.trim_start_matches("```")
-                   .trim_end_matches("```")
-                   .trim()
-        } else {
-            content
-        };
-
-        let ai_response: AiAssistantResponse = match serde_json::from_str(json_content) {
-            Ok(resp) => resp,
-            Err(e) => {
-                tracing::error!("Failed to parse AI response: {}", e);
-                tracing::error!("Raw response: {}", claude_response.content);
-                tracing::error!("Cleaned content: {}", json_content);
-                self.mark_session_failed(
-                    session_id,
-                    format!("Failed to parse AI response: {}", e),
-                    "failed"
-                ).await?;
-                return Err(AppError::Internal(anyhow::anyhow!("AI returned invalid response")));
-            }
-        };
+        self.claude_service.cache_response(&claude_response).await;
 
         // 5. Handle response based on type
         match ai_response {
@@ -0,0 +1,733 @@
+/// NL Query Report Service
+///
+/// Exports NL query results as CSV or XLSX and runs saved favorites on daily/weekly
+/// schedules. Each scheduled run re-executes the query (one NL query from the monthly
+/// quota), stores the report encrypted on disk, records it in the report history and
+/// delivers it as a notification and, when enabled and SMTP is configured, by email.
+
+use chrono::Utc;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::{
+    middleware::error_handling::{AppError, Result},
+    models::alerts::AlertPayload,
+    models::nl_query::*,
+    repositories::UserRepository,
+    services::{EmailAttachment, EmailService, NlQueryService, NotificationService},
+    utils::encrypted_file_storage::EncryptedFileStorage,
+};
+
+/// Due schedules claimed per scheduler tick
+const SCHEDULE_BATCH_SIZE: i64 = 50;
+
+/// Reports are stored under this directory of FILE_STORAGE_PATH
+const REPORT_STORAGE_DIR: &str = "nl_reports";
+
+pub struct NlReportService {
+    db_pool: PgPool,
+    storage: EncryptedFileStorage,
+    encryption_key: String,
+}
+
+impl NlReportService {
+    pub fn new(db_pool: PgPool, file_storage_path: &str, encryption_key: &str) -> Result<Self> {
+        let storage = EncryptedFileStorage::new(
+            std::path::Path::new(file_storage_path).join(REPORT_STORAGE_DIR),
+            encryption_key,
+        )?;
+
+        Ok(Self {
+            db_pool,
+            storage,
+            encryption_key: encryption_key.to_string(),
+        })
+    }
+
+    // ========================================================================
+    // Exports
+    // ========================================================================
+
+    /// Encode the stored results of one of the user's query sessions
+    pub async fn export_session(
+        &self,
+        user_id: Uuid,
+        session_id: Uuid,
+        format: ReportFormat,
+    ) -> Result<(String, Vec<u8>)> {
+        let session = self.nl_query_service()?.get_session(session_id).await?;
+        if session.user_id != user_id {
+            return Err(AppError::Forbidden("Access denied".to_string()));
+        }
+        if session.status != "success" {
+            return Err(AppError::BadRequest("Only successful queries can be exported".to_string()));
+        }
+
+        let rows = session_rows(&session);
+        let (headers, rows) = result_table(&rows);
+        let data = encode_report(format, &headers, &rows)?;
+
+        Ok((report_filename(&session.query_text, format), data))
+    }
+
+    // ========================================================================
+    // Schedules
+    // ========================================================================
+
+    pub async fn create_schedule(
+        &self,
+        user_id: Uuid,
+        request: &CreateReportScheduleRequest,
+    ) -> Result<NlReportSchedule> {
+        let favorite = sqlx::query_as::<_, (String, Option<String>)>(
+            "SELECT query_text, description FROM nl_query_favorites WHERE id = $1 AND user_id = $2"
+        )
+        .bind(request.favorite_id)
+        .bind(user_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Favorite query not found".to_string()))?;
+
+        let format = ReportFormat::parse(request.format.as_deref()).map_err(AppError::BadRequest)?;
+        let (day_of_week, hour_utc) = schedule_time(request.frequency, request.day_of_week, request.hour_utc)?;
+        let name = request.name.as_deref()
+            .map(str::trim)
+            .filter(|n| !n.is_empty())
+            .map(String::from)
+            .or(favorite.1)
+            .unwrap_or(favorite.0);
+
+        self.check_schedule_quota(user_id, None, request.frequency).await?;
+
+        let schedule = sqlx::query_as::<_, NlReportSchedule>(
+            r#"
+            INSERT INTO nl_query_report_schedules (
+                user_id, favorite_id, name, frequency, day_of_week, hour_utc, format, email_delivery, next_run_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING *
+            "#
+        )
+        .bind(user_id)
+        .bind(request.favorite_id)
+        .bind(&name)
+        .bind(request.frequency.as_str())
+        .bind(day_of_week)
+        .bind(hour_utc)
+        .bind(format.as_str())
+        .bind(request.email_delivery)
+        .bind(next_report_run(Utc::now(), request.frequency, day_of_week, hour_utc))
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        tracing::info!(
+            "Report schedule {} created for user {} ({} at {:02}:00 UTC)",
+            schedule.id, user_id, schedule.frequency, schedule.hour_utc
+        );
+
+        Ok(schedule)
+    }
+
+    pub async fn list_schedules(&self, user_id: Uuid) -> Result<Vec<NlReportSchedule>> {
+        let schedules = sqlx::query_as::<_, NlReportSchedule>(
+            "SELECT * FROM nl_query_report_schedules WHERE user_id = $1 ORDER BY created_at DESC"
+        )
+        .bind(user_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(schedules)
+    }
+
+    pub async fn get_schedule(&self, user_id: Uuid, schedule_id: Uuid) -> Result<NlReportSchedule> {
+        sqlx::query_as::<_, NlReportSchedule>(
+            "SELECT * FROM nl_query_report_schedules WHERE id = $1 AND user_id = $2"
+        )
+        .bind(schedule_id)
+        .bind(user_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Report schedule not found".to_string()))
+    }
+
+    /// Update a schedule; changing its timing (or resuming it) recomputes the next run
+    pub async fn update_schedule(
+        &self,
+        user_id: Uuid,
+        schedule_id: Uuid,
+        request: &UpdateReportScheduleRequest,
+    ) -> Result<NlReportSchedule> {
+        let current = self.get_schedule(user_id, schedule_id).await?;
+
+        let frequency = request.frequency
+            .or_else(|| ReportFrequency::from_str(&current.frequency))
+            .unwrap_or(ReportFrequency::Daily);
+        let (day_of_week, hour_utc) = schedule_time(
+            frequency,
+            request.day_of_week.or(current.day_of_week),
+            request.hour_utc.or(Some(current.hour_utc)),
+        )?;
+        let format = match &request.format {
+            Some(format) => ReportFormat::parse(Some(format)).map_err(AppError::BadRequest)?.as_str().to_string(),
+            None => current.format.clone(),
+        };
+        let name = request.name.as_deref().map(str::trim).filter(|n| !n.is_empty()).map(String::from);
+        let is_active = request.is_active.unwrap_or(current.is_active);
+
+        let more_runs = frequency.max_monthly_runs() > ReportFrequency::from_str(&current.frequency)
+            .map(|f| f.max_monthly_runs())
+            .unwrap_or(0);
+        if is_active && (!current.is_active || more_runs) {
+            self.check_schedule_quota(user_id, Some(schedule_id), frequency).await?;
+        }
+
+        let schedule = sqlx::query_as::<_, NlReportSchedule>(
+            r#"
+            UPDATE nl_query_report_schedules
+            SET name = COALESCE($1, name),
+                frequency = $2,
+                day_of_week = $3,
+                hour_utc = $4,
+                format = $5,
+                email_delivery = COALESCE($6, email_delivery),
+                is_active = $7,
+                next_run_at = $8,
+                updated_at = NOW()
+            WHERE id = $9 AND user_id = $10
+            RETURNING *
+            "#
+        )
+        .bind(name)
+        .bind(frequency.as_str())
+        .bind(day_of_week)
+        .bind(hour_utc)
+        .bind(format)
+        .bind(request.email_delivery)
+        .bind(is_active)
+        .bind(next_report_run(Utc::now(), frequency, day_of_week, hour_utc))
+        .bind(schedule_id)
+        .bind(user_id)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        Ok(schedule)
+    }
+
+    pub async fn delete_schedule(&self, user_id: Uuid, schedule_id: Uuid) -> Result<()> {
+        let result = sqlx::query("DELETE FROM nl_query_report_schedules WHERE id = $1 AND user_id = $2")
+            .bind(schedule_id)
+            .bind(user_id)
+            .execute(&self.db_pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Report schedule not found".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Refuse schedules whose runs could not fit in the monthly NL query limit alongside
+    /// the user's other active schedules
+    async fn check_schedule_quota(
+        &self,
+        user_id: Uuid,
+        excluding: Option<Uuid>,
+        frequency: ReportFrequency,
+    ) -> Result<()> {
+        let (limit, _, remaining) = self.nl_query_service()?.get_quota_status(user_id).await?;
+        if remaining <= 0 {
+            return Err(AppError::QuotaExceeded(
+                "Monthly NL query limit reached. Scheduled reports can be created after the monthly reset.".to_string()
+            ));
+        }
+
+        let frequencies: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT frequency FROM nl_query_report_schedules
+            WHERE user_id = $1 AND is_active AND ($2::uuid IS NULL OR id <> $2)
+            "#
+        )
+        .bind(user_id)
+        .bind(excluding)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let projected: i32 = frequencies
+            .iter()
+            .filter_map(|f| ReportFrequency::from_str(f))
+            .map(|f| f.max_monthly_runs())
+            .sum::<i32>()
+            + frequency.max_monthly_runs();
+
+        if projected > limit {
+            return Err(AppError::BadRequest(format!(
+                "Active report schedules would use up to {} NL queries a month, above your limit of {}",
+                projected, limit
+            )));
+        }
+        Ok(())
+    }
+
+    // ========================================================================
+    // Report History
+    // ========================================================================
+
+    pub async fn list_files(&self, user_id: Uuid, query: &ReportFilesQuery) -> Result<Vec<NlReportFile>> {
+        let files = sqlx::query_as::<_, NlReportFile>(
+            r#"
+            SELECT * FROM nl_query_report_files
+            WHERE user_id = $1 AND ($2::uuid IS NULL OR schedule_id = $2)
+            ORDER BY created_at DESC
+            LIMIT $3
+            "#
+        )
+        .bind(user_id)
+        .bind(query.schedule_id)
+        .bind(query.limit.unwrap_or(50).clamp(1, 200))
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(files)
+    }
+
+    /// A generated report file and its decrypted content
+    pub async fn download_file(&self, user_id: Uuid, file_id: Uuid) -> Result<(NlReportFile, Vec<u8>)> {
+        let file = sqlx::query_as::<_, NlReportFile>(
+            "SELECT * FROM nl_query_report_files WHERE id = $1 AND user_id = $2"
+        )
+        .bind(file_id)
+        .bind(user_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Report file not found".to_string()))?;
+
+        let path = file.file_path.as_deref()
+            .ok_or_else(|| AppError::NotFound("This report run did not produce a file".to_string()))?;
+        let data = self.storage.read_encrypted_file(path)?;
+
+        Ok((file, data))
+    }
+
+    // ========================================================================
+    // Scheduled Runs
+    // ========================================================================
+
+    /// Claim due schedules (advancing their next run first, so a slow or failed run is
+    /// never repeated) and run them. Returns the number of schedules run.
+    pub async fn run_due_schedules(&self) -> Result<usize> {
+        let mut tx = self.db_pool.begin().await?;
+
+        let due = sqlx::query_as::<_, NlReportSchedule>(
+            r#"
+            SELECT * FROM nl_query_report_schedules
+            WHERE is_active AND next_run_at <= NOW()
+            ORDER BY next_run_at
+            LIMIT $1
+            FOR UPDATE SKIP LOCKED
+            "#
+        )
+        .bind(SCHEDULE_BATCH_SIZE)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let now = Utc::now();
+        for schedule in &due {
+            let frequency = ReportFrequency::from_str(&schedule.frequency).unwrap_or(ReportFrequency::Daily);
+            sqlx::query("UPDATE nl_query_report_schedules SET next_run_at = $1, last_run_at = $2 WHERE id = $3")
+                .bind(next_report_run(now, frequency, schedule.day_of_week, schedule.hour_utc))
+                .bind(now)
+                .bind(schedule.id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+
+        for schedule in &due {
+            let status = match self.run_schedule(schedule).await {
+                Ok(status) => status,
+                Err(e) => {
+                    tracing::error!("Report schedule {} failed: {}", schedule.id, e);
+                    if let Err(e) = self.record_run(schedule, "failed", None, Some(&e.to_string())).await {
+                        tracing::error!("Failed to record failed run of report schedule {}: {}", schedule.id, e);
+                    }
+                    "failed"
+                }
+            };
+
+            sqlx::query("UPDATE nl_query_report_schedules SET last_status = $1 WHERE id = $2")
+                .bind(status)
+                .bind(schedule.id)
+                .execute(&self.db_pool)
+                .await?;
+        }
+
+        Ok(due.len())
+    }
+
+    /// Run one schedule, returning the status of the report it produced
+    async fn run_schedule(&self, schedule: &NlReportSchedule) -> Result<&'static str> {
+        let notifications = NotificationService::new(self.db_pool.clone());
+        let nl_queries = self.nl_query_service()?;
+
+        let (_, _, remaining) = nl_queries.get_quota_status(schedule.user_id).await?;
+        if remaining <= 0 {
+            return self.skip_for_quota(schedule, &notifications).await;
+        }
+
+        let query_text: String = sqlx::query_scalar("SELECT query_text FROM nl_query_favorites WHERE id = $1")
+            .bind(schedule.favorite_id)
+            .fetch_one(&self.db_pool)
+            .await?;
+
+        let session = match nl_queries.execute_query(schedule.user_id, query_text).await {
+            Ok(session) => session,
+            Err(AppError::QuotaExceeded(_)) => return self.skip_for_quota(schedule, &notifications).await,
+            Err(e) => return Err(e),
+        };
+
+        let format = ReportFormat::parse(Some(&schedule.format)).map_err(AppError::BadRequest)?;
+        let (headers, rows) = result_table(&session_rows(&session));
+        let data = encode_report(format, &headers, &rows)?;
+        let filename = report_filename(&schedule.name, format);
+
+        let file_id = Uuid::new_v4();
+        let (file_path, _) = self.storage.save_encrypted_file(file_id, &filename, &data)?;
+
+        let file = sqlx::query_as::<_, NlReportFile>(
+            r#"
+            INSERT INTO nl_query_report_files (
+                id, user_id, schedule_id, session_id, status, format, filename, file_path, file_size_bytes, row_count
+            ) VALUES ($1, $2, $3, $4, 'generated', $5, $6, $7, $8, $9)
+            RETURNING *
+            "#
+        )
+        .bind(file_id)
+        .bind(schedule.user_id)
+        .bind(schedule.id)
+        .bind(session.id)
+        .bind(format.as_str())
+        .bind(&filename)
+        .bind(&file_path)
+        .bind(data.len() as i64)
+        .bind(rows.len() as i32)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        notifications.create_alert(AlertPayload::new_report_ready(
+            schedule.user_id,
+            &schedule.name,
+            file.id,
+            rows.len() as i32,
+            format.as_str(),
+        )).await?;
+
+        if schedule.email_delivery {
+            let attachment = EmailAttachment {
+                filename,
+                content_type: format.content_type().to_string(),
+                data,
+            };
+            match self.email_report(schedule, rows.len(), attachment).await {
+                Ok(true) => {
+                    sqlx::query("UPDATE nl_query_report_files SET emailed = true WHERE id = $1")
+                        .bind(file.id)
+                        .execute(&self.db_pool)
+                        .await?;
+                }
+                Ok(false) => tracing::warn!("Report schedule {} requests email but SMTP is not configured", schedule.id),
+                // The report is generated and in the history either way
+                Err(e) => tracing::error!("Failed to email report {}: {}", file.id, e),
+            }
+        }
+
+        tracing::info!(
+            "Report schedule {} generated {} ({} rows)",
+            schedule.id, file.id, rows.len()
+        );
+
+        Ok("generated")
+    }
+
+    async fn skip_for_quota(
+        &self,
+        schedule: &NlReportSchedule,
+        notifications: &NotificationService,
+    ) -> Result<&'static str> {
+        let reason = "the monthly NL query limit has been reached";
+        tracing::info!("Report schedule {} skipped: {}", schedule.id, reason);
+
+        self.record_run(schedule, "skipped_quota", None, Some(reason)).await?;
+        notifications.create_alert(AlertPayload::new_report_skipped(
+            schedule.user_id,
+            &schedule.name,
+            schedule.id,
+            reason,
+        )).await?;
+
+        Ok("skipped_quota")
+    }
+
+    async fn record_run(
+        &self,
+        schedule: &NlReportSchedule,
+        status: &str,
+        session_id: Option<Uuid>,
+        error_message: Option<&str>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO nl_query_report_files (user_id, schedule_id, session_id, status, format, error_message)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#
+        )
+        .bind(schedule.user_id)
+        .bind(schedule.id)
+        .bind(session_id)
+        .bind(status)
+        .bind(&schedule.format)
+        .bind(error_message)
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Email the report to the schedule owner; `false` when email is not configured
+    async fn email_report(
+        &self,
+        schedule: &NlReportSchedule,
+        row_count: usize,
+        attachment: EmailAttachment,
+    ) -> Result<bool> {
+        let Some(email) = EmailService::from_env()? else {
+            return Ok(false);
+        };
+
+        let user = UserRepository::new(self.db_pool.clone(), &self.encryption_key)?
+            .find_by_id(schedule.user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+        email.send(
+            &user.email,
+            &format!("Atlas Pharma report: {}", schedule.name),
+            &format!(
+                "Your {} report \"{}\" is attached ({} row{}).\n\nManage report schedules in Atlas Pharma under Reports.",
+                schedule.frequency,
+                schedule.name,
+                row_count,
+                if row_count == 1 { "" } else { "s" }
+            ),
+            Some(attachment),
+        ).await?;
+
+        Ok(true)
+    }
+
+    fn nl_query_service(&self) -> Result<NlQueryService> {
+        let claude_api_key = std::env::var("ANTHROPIC_API_KEY")
+            .map_err(|_| AppError::Internal(anyhow::anyhow!("ANTHROPIC_API_KEY not configured")))?;
+        Ok(NlQueryService::new(self.db_pool.clone(), claude_api_key))
+    }
+}
+
+// ============================================================================
+// Report Encoding
+// ============================================================================
+
+/// Result rows of a session: query results, or a single `answer` row for a
+/// conversational response
+fn session_rows(session: &NlQuerySession) -> Vec<serde_json::Value> {
+    match &session.result_data {
+        Some(serde_json::Value::Array(rows)) => rows.clone(),
+        Some(value @ serde_json::Value::Object(_)) => vec![value.clone()],
+        _ => Vec::new(),
+    }
+}
+
+/// `<name>_<date>.<ext>` with the name reduced to a safe slug
+fn report_filename(name: &str, format: ReportFormat) -> String {
+    let slug: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect::<String>()
+        .split('_')
+        .filter(|part| !part.is_empty())
+        .take(8)
+        .collect::<Vec<_>>()
+        .join("_");
+    let slug = if slug.is_empty() { "report".to_string() } else { slug };
+
+    format!("{}_{}.{}", slug, Utc::now().format("%Y-%m-%d"), format.as_str())
+}
+
+pub fn encode_report(
+    format: ReportFormat,
+    headers: &[String],
+    rows: &[Vec<serde_json::Value>],
+) -> Result<Vec<u8>> {
+    match format {
+        ReportFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(Vec::new());
+            writer.write_record(headers)
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("CSV encoding failed: {}", e)))?;
+            for row in rows {
+                writer.write_record(row.iter().map(cell_text))
+                    .map_err(|e| AppError::Internal(anyhow::anyhow!("CSV encoding failed: {}", e)))?;
+            }
+            writer.into_inner()
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("CSV encoding failed: {}", e)))
+        }
+        ReportFormat::Xlsx => encode_xlsx(headers, rows)
+            .map_err(|e| AppError::Internal(anyhow::anyhow!("XLSX encoding failed: {}", e))),
+    }
+}
+
+fn encode_xlsx(
+    headers: &[String],
+    rows: &[Vec<serde_json::Value>],
+) -> std::result::Result<Vec<u8>, rust_xlsxwriter::XlsxError> {
+    use rust_xlsxwriter::{Format, Workbook};
+
+    let mut workbook = Workbook::new();
+    let bold = Format::new().set_bold();
+    let sheet = workbook.add_worksheet();
+
+    for (col, header) in headers.iter().enumerate() {
+        sheet.write_string_with_format(0, col as u16, header, &bold)?;
+    }
+
+    for (idx, row) in rows.iter().enumerate() {
+        let r = idx as u32 + 1;
+        for (col, value) in row.iter().enumerate() {
+            let c = col as u16;
+            match value {
+                serde_json::Value::Null => {}
+                serde_json::Value::Bool(b) => { sheet.write_boolean(r, c, *b)?; }
+                serde_json::Value::Number(n) => match n.as_f64() {
+                    Some(f) => { sheet.write_number(r, c, f)?; }
+                    None => { sheet.write_string(r, c, n.to_string())?; }
+                },
+                other => { sheet.write_string(r, c, cell_text(other))?; }
+            }
+        }
+    }
+
+    sheet.set_freeze_panes(1, 0)?;
+    sheet.autofit();
+
+    workbook.save_to_buffer()
+}
+
+// ============================================================================
+// Report Scheduler
+// ============================================================================
+
+/// Runs due report schedules on a fixed interval
+pub struct NlReportScheduler {
+    service: NlReportService,
+    interval_minutes: u64,
+}
+
+impl NlReportScheduler {
+    pub fn new(pool: PgPool, file_storage_path: &str, encryption_key: &str) -> Result<Self> {
+        let interval_minutes = std::env::var("NL_REPORT_SCHEDULER_INTERVAL_MINUTES")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .filter(|m| *m > 0)
+            .unwrap_or(15);
+
+        Ok(Self {
+            service: NlReportService::new(pool, file_storage_path, encryption_key)?,
+            interval_minutes,
+        })
+    }
+
+    /// Run the scheduler loop
+    pub async fn run(&self) {
+        let mut ticker = tokio::time::interval(std::time::Duration::from_secs(self.interval_minutes * 60));
+
+        tracing::info!("NL report scheduler started - checking schedules every {} minutes", self.interval_minutes);
+
+        loop {
+            let deadline = ticker.tick().await;
+            crate::middleware::metrics::record_scheduler_lag("nl_reports", deadline);
+            if crate::services::MaintenanceService::pauses("nl_reports") {
+                continue;
+            }
+
+            match self.service.run_due_schedules().await {
+                Ok(0) => {}
+                Ok(count) => tracing::info!("Ran {} scheduled NL query report(s)", count),
+                Err(e) => tracing::error!("Scheduled NL query reports failed: {}", e),
+            }
+        }
+    }
+}
+
+/// Validated weekday and hour for a schedule; weekly schedules default to Monday
+fn schedule_time(
+    frequency: ReportFrequency,
+    day_of_week: Option<i16>,
+    hour_utc: Option<i16>,
+) -> Result<(Option<i16>, i16)> {
+    let hour_utc = hour_utc.unwrap_or(6);
+    if !(0..=23).contains(&hour_utc) {
+        return Err(AppError::BadRequest("hour_utc must be between 0 and 23".to_string()));
+    }
+
+    let day_of_week = match frequency {
+        ReportFrequency::Daily => None,
+        ReportFrequency::Weekly => {
+            let day = day_of_week.unwrap_or(0);
+            if !(0..=6).contains(&day) {
+                return Err(AppError::BadRequest("day_of_week must be between 0 (Monday) and 6 (Sunday)".to_string()));
+            }
+            Some(day)
+        }
+    };
+
+    Ok((day_of_week, hour_utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_csv_report() {
+        let headers = vec!["ndc".to_string(), "quantity".to_string()];
+        let rows = vec![
+            vec![serde_json::json!("12345-678-90"), serde_json::json!(10)],
+            vec![serde_json::json!("Amoxicillin, 500mg"), serde_json::Value::Null],
+        ];
+
+        let csv = String::from_utf8(encode_report(ReportFormat::Csv, &headers, &rows).unwrap()).unwrap();
+        assert_eq!(csv, "ndc,quantity\n12345-678-90,10\n\"Amoxicillin, 500mg\",\n");
+    }
+
+    #[test]
+    fn test_encode_xlsx_report() {
+        let data = encode_report(
+            ReportFormat::Xlsx,
+            &["ndc".to_string()],
+            &[vec![serde_json::json!("12345-678-90")]],
+        ).unwrap();
+        // XLSX is a ZIP archive
+        assert_eq!(&data[0..2], b"PK");
+    }
+
+    #[test]
+    fn test_report_filename() {
+        let name = report_filename("Expiring stock / next 30 days!", ReportFormat::Xlsx);
+        assert!(name.starts_with("expiring_stock_next_30_days_"));
+        assert!(name.ends_with(".xlsx"));
+        assert!(report_filename("???", ReportFormat::Csv).starts_with("report_"));
+    }
+
+    #[test]
+    fn test_schedule_time() {
+        assert_eq!(schedule_time(ReportFrequency::Daily, Some(3), Some(8)).unwrap(), (None, 8));
+        assert_eq!(schedule_time(ReportFrequency::Weekly, None, None).unwrap(), (Some(0), 6));
+        assert!(schedule_time(ReportFrequency::Weekly, Some(7), None).is_err());
+        assert!(schedule_time(ReportFrequency::Daily, None, Some(24)).is_err());
+    }
+}
@@ -6,13 +6,17 @@
 /// - Fetching user notifications with filtering
 /// - Marking notifications as read/dismissed
 /// - Managing user alert preferences
-/// - Managing marketplace watchlists
+/// - Managing marketplace watchlists and their price drop / back in stock triggers
 
 use crate::{
     middleware::error_handling::{Result, AppError},
     models::alerts::*,
+    services::AlertChannelService,
 };
+use chrono::{DateTime, NaiveDateTime, Utc};
+use rust_decimal::Decimal;
 use sqlx::PgPool;
+use std::collections::HashMap;
 use uuid::Uuid;
 
 pub struct NotificationService {
@@ -28,16 +32,35 @@ impl NotificationService {
     // ALERT NOTIFICATION CRUD
     // ========================================================================
 
-    /// Create a new alert notification from payload
+    /// Create a new alert notification from payload. Non-critical alerts are held back
+    /// when the user has digests or quiet hours configured.
     pub async fn create_alert(&self, payload: AlertPayload) -> Result<AlertNotification> {
+        let preferences = self.get_user_preferences(payload.user_id).await?;
+        let local_now: NaiveDateTime = sqlx::query_scalar("SELECT (NOW() AT TIME ZONE $1)::timestamp")
+            .bind(&preferences.timezone)
+            .fetch_one(&self.db_pool)
+            .await?;
+        let held_until = hold_until(local_now, &preferences, &payload.severity);
+
+        self.insert_alert(payload, held_until, &preferences.timezone).await
+    }
+
+    /// Insert a notification held until the local time `held_until` (in `timezone`), or
+    /// delivered right away when `None`
+    async fn insert_alert(
+        &self,
+        payload: AlertPayload,
+        held_until: Option<NaiveDateTime>,
+        timezone: &str,
+    ) -> Result<AlertNotification> {
         let notification = sqlx::query_as!(
             AlertNotification,
             r#"
             INSERT INTO alert_notifications (
                 user_id, alert_type, severity, title, message,
-                inventory_id, related_user_id, metadata, action_url
+                inventory_id, related_user_id, metadata, action_url, held_until
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10::timestamp AT TIME ZONE $11)
             RETURNING *
             "#,
             payload.user_id,
@@ -48,21 +71,125 @@ impl NotificationService {
             payload.inventory_id,
             payload.related_user_id,
             payload.metadata,
-            payload.action_url
+            payload.action_url,
+            held_until,
+            timezone
         )
         .fetch_one(&self.db_pool)
         .await?;
 
         tracing::info!(
-            "Alert created: type={}, user={}, severity={}",
+            "Alert created: type={}, user={}, severity={}, held_until={:?}",
             notification.alert_type,
             notification.user_id,
-            notification.severity
+            notification.severity,
+            notification.held_until
         );
 
+        // Post to the Slack/Teams channels this alert type is routed to
+        if notification.held_until.is_none() {
+            AlertChannelService::deliver_in_background(self.db_pool.clone(), notification.clone());
+        }
+
         Ok(notification)
     }
 
+    /// Release held notifications that are due. Per user, a single due notification is
+    /// delivered as is; several are summarized in one digest notification and released
+    /// as read. Returns the number of notifications delivered (digests count once).
+    pub async fn release_held_notifications(&self) -> Result<i32> {
+        let mut tx = self.db_pool.begin().await?;
+
+        let due = sqlx::query_as::<_, AlertNotification>(
+            r#"
+            SELECT * FROM alert_notifications
+            WHERE held_until IS NOT NULL AND held_until <= NOW()
+            ORDER BY user_id, created_at
+            LIMIT 5000
+            FOR UPDATE SKIP LOCKED
+            "#
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut by_user: HashMap<Uuid, Vec<AlertNotification>> = HashMap::new();
+        for notification in due {
+            by_user.entry(notification.user_id).or_default().push(notification);
+        }
+
+        let user_ids: Vec<Uuid> = by_user.keys().copied().collect();
+        let frequencies: HashMap<Uuid, String> = sqlx::query_as::<_, (Uuid, String)>(
+            "SELECT user_id, digest_frequency FROM user_alert_preferences WHERE user_id = ANY($1)"
+        )
+        .bind(&user_ids)
+        .fetch_all(&mut *tx)
+        .await?
+        .into_iter()
+        .collect();
+
+        let mut delivered = Vec::new();
+        for (user_id, notifications) in by_user {
+            let ids: Vec<Uuid> = notifications.iter().map(|n| n.id).collect();
+
+            if notifications.len() == 1 {
+                let released = sqlx::query_as::<_, AlertNotification>(
+                    "UPDATE alert_notifications SET held_until = NULL WHERE id = $1 RETURNING *"
+                )
+                .bind(ids[0])
+                .fetch_one(&mut *tx)
+                .await?;
+                delivered.push(released);
+                continue;
+            }
+
+            let label = match frequencies.get(&user_id).and_then(|f| DigestFrequency::parse(f)) {
+                Some(DigestFrequency::Daily) => "Daily digest",
+                Some(DigestFrequency::Weekly) => "Weekly digest",
+                _ => "During quiet hours",
+            };
+            let payload = AlertPayload::new_digest(user_id, label, &notifications);
+
+            let digest = sqlx::query_as::<_, AlertNotification>(
+                r#"
+                INSERT INTO alert_notifications (user_id, alert_type, severity, title, message, metadata, action_url)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                RETURNING *
+                "#
+            )
+            .bind(user_id)
+            .bind(payload.alert_type.as_str())
+            .bind(payload.severity.as_str())
+            .bind(&payload.title)
+            .bind(&payload.message)
+            .bind(&payload.metadata)
+            .bind(&payload.action_url)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            sqlx::query(
+                r#"
+                UPDATE alert_notifications
+                SET held_until = NULL, is_read = TRUE, read_at = NOW(), digest_id = $2
+                WHERE id = ANY($1)
+                "#
+            )
+            .bind(&ids)
+            .bind(digest.id)
+            .execute(&mut *tx)
+            .await?;
+
+            delivered.push(digest);
+        }
+
+        tx.commit().await?;
+
+        for notification in &delivered {
+            AlertChannelService::deliver_in_background(self.db_pool.clone(), notification.clone());
+        }
+
+        Ok(delivered.len() as i32)
+    }
+
     /// Get notifications for a user with optional filtering
     pub async fn get_user_notifications(
         &self,
@@ -74,29 +201,34 @@ impl NotificationService {
 
         // Build query conditionally
         let mut base_query = String::from(
-            "SELECT * FROM alert_notifications WHERE user_id = $1 AND is_dismissed = FALSE"
+            "SELECT * FROM alert_notifications WHERE user_id = $1 AND is_dismissed = FALSE AND held_until IS NULL AND archived_at IS NULL"
         );
 
         if query.unread_only == Some(true) {
             base_query.push_str(" AND is_read = FALSE");
         }
 
-        if let Some(ref alert_type) = query.alert_type {
-            base_query.push_str(&format!(" AND alert_type = '{}'", alert_type));
+        if query.alert_type.is_some() {
+            base_query.push_str(" AND alert_type = $4");
         }
 
         base_query.push_str(" ORDER BY created_at DESC LIMIT $2 OFFSET $3");
 
-        let notifications = sqlx::query_as::<_, AlertNotification>(&base_query)
+        let mut notifications_query = sqlx::query_as::<_, AlertNotification>(&base_query)
             .bind(user_id)
             .bind(limit)
-            .bind(offset)
+            .bind(offset);
+        if let Some(ref alert_type) = query.alert_type {
+            notifications_query = notifications_query.bind(alert_type);
+        }
+
+        let notifications = notifications_query
             .fetch_all(&self.db_pool)
             .await?;
 
         // Get total counts
         let total_unread: i64 = sqlx::query_scalar!(
-            "SELECT COUNT(*) FROM alert_notifications WHERE user_id = $1 AND is_read = FALSE AND is_dismissed = FALSE",
+            "SELECT COUNT(*) FROM alert_notifications WHERE user_id = $1 AND is_read = FALSE AND is_dismissed = FALSE AND held_until IS NULL AND archived_at IS NULL",
             user_id
         )
         .fetch_one(&self.db_pool)
@@ -104,7 +236,7 @@ impl NotificationService {
         .unwrap_or(0);
 
         let total_notifications: i64 = sqlx::query_scalar!(
-            "SELECT COUNT(*) FROM alert_notifications WHERE user_id = $1 AND is_dismissed = FALSE",
+            "SELECT COUNT(*) FROM alert_notifications WHERE user_id = $1 AND is_dismissed = FALSE AND held_until IS NULL AND archived_at IS NULL",
             user_id
         )
         .fetch_one(&self.db_pool)
@@ -139,7 +271,7 @@ impl NotificationService {
     /// Mark all notifications as read for a user
     pub async fn mark_all_read(&self, user_id: Uuid) -> Result<u64> {
         let result = sqlx::query!(
-            "UPDATE alert_notifications SET is_read = TRUE WHERE user_id = $1 AND is_read = FALSE",
+            "UPDATE alert_notifications SET is_read = TRUE WHERE user_id = $1 AND is_read = FALSE AND held_until IS NULL AND archived_at IS NULL",
             user_id
         )
         .execute(&self.db_pool)
@@ -165,6 +297,135 @@ impl NotificationService {
         Ok(())
     }
 
+    /// Dismiss active notifications matching the filters (types and/or created before a
+    /// date, optionally only read ones). Returns the number dismissed.
+    pub async fn bulk_dismiss(&self, user_id: Uuid, request: &BulkDismissRequest) -> Result<u64> {
+        if request.alert_types.is_empty() && request.before.is_none() {
+            return Err(AppError::BadRequest(
+                "Specify alert_types and/or before to select the notifications to dismiss".to_string(),
+            ));
+        }
+
+        let alert_types: Vec<&str> = request.alert_types.iter().map(|t| t.as_str()).collect();
+
+        let result = sqlx::query(
+            r#"
+            UPDATE alert_notifications
+            SET is_dismissed = TRUE
+            WHERE user_id = $1
+              AND is_dismissed = FALSE
+              AND held_until IS NULL
+              AND archived_at IS NULL
+              AND (cardinality($2::TEXT[]) = 0 OR alert_type = ANY($2))
+              AND ($3::TIMESTAMPTZ IS NULL OR created_at < $3)
+              AND (NOT $4 OR is_read = TRUE)
+            "#,
+        )
+        .bind(user_id)
+        .bind(&alert_types)
+        .bind(request.before)
+        .bind(request.read_only)
+        .execute(&self.db_pool)
+        .await?;
+
+        tracing::info!("Bulk dismissed {} notifications for user {}", result.rows_affected(), user_id);
+
+        Ok(result.rows_affected())
+    }
+
+    /// Archived notifications, newest first
+    pub async fn get_archived_notifications(
+        &self,
+        user_id: Uuid,
+        query: &ArchivedNotificationsQuery,
+    ) -> Result<ArchivedNotificationsResponse> {
+        let page = query.page.unwrap_or(1).max(1);
+        let page_size = query.page_size.unwrap_or(50).clamp(1, 100);
+        let alert_type = query.alert_type.as_ref().map(|t| t.as_str());
+
+        let notifications = sqlx::query_as::<_, AlertNotification>(
+            r#"
+            SELECT * FROM alert_notifications
+            WHERE user_id = $1 AND archived_at IS NOT NULL AND is_dismissed = FALSE
+              AND ($2::TEXT IS NULL OR alert_type = $2)
+            ORDER BY created_at DESC
+            LIMIT $3 OFFSET $4
+            "#,
+        )
+        .bind(user_id)
+        .bind(alert_type)
+        .bind(page_size)
+        .bind((page - 1) * page_size)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let total: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*) FROM alert_notifications
+            WHERE user_id = $1 AND archived_at IS NOT NULL AND is_dismissed = FALSE
+              AND ($2::TEXT IS NULL OR alert_type = $2)
+            "#,
+        )
+        .bind(user_id)
+        .bind(alert_type)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        Ok(ArchivedNotificationsResponse {
+            notifications: notifications.into_iter().map(Into::into).collect(),
+            total,
+            page,
+            page_size,
+        })
+    }
+
+    /// Archive notifications past the archive age and delete those past the delete age.
+    /// Deletes run in batches to keep locks short. Returns (archived, deleted).
+    pub async fn apply_retention(&self, policy: RetentionPolicy) -> Result<(u64, u64)> {
+        const DELETE_BATCH_SIZE: i64 = 5000;
+
+        let archived = sqlx::query(
+            r#"
+            UPDATE alert_notifications
+            SET archived_at = NOW()
+            WHERE archived_at IS NULL
+              AND held_until IS NULL
+              AND created_at < NOW() - make_interval(days => $1)
+            "#,
+        )
+        .bind(policy.archive_after_days as i32)
+        .execute(&self.db_pool)
+        .await?
+        .rows_affected();
+
+        let mut deleted = 0;
+        loop {
+            let batch = sqlx::query(
+                r#"
+                DELETE FROM alert_notifications
+                WHERE id IN (
+                    SELECT id FROM alert_notifications
+                    WHERE created_at < NOW() - make_interval(days => $1)
+                      AND held_until IS NULL
+                    LIMIT $2
+                )
+                "#,
+            )
+            .bind(policy.delete_after_days as i32)
+            .bind(DELETE_BATCH_SIZE)
+            .execute(&self.db_pool)
+            .await?
+            .rows_affected();
+
+            deleted += batch;
+            if batch < DELETE_BATCH_SIZE as u64 {
+                break;
+            }
+        }
+
+        Ok((archived, deleted))
+    }
+
     /// Get unread notification count
     pub async fn get_unread_count(&self, user_id: Uuid) -> Result<i64> {
         let count = sqlx::query_scalar!(
@@ -219,6 +480,9 @@ impl NotificationService {
         user_id: Uuid,
         update: UpdateAlertPreferencesRequest,
     ) -> Result<UserAlertPreferences> {
+        self.validate_delivery_schedule(&update).await?;
+        let reschedule = update.changes_delivery_schedule();
+
         // Build dynamic update query
         let mut updates = Vec::new();
         let mut param_count = 1;
@@ -251,6 +515,42 @@ impl NotificationService {
             param_count += 1;
             updates.push(format!("in_app_notifications_enabled = ${}", param_count));
         }
+        let channel_routes = match update.channel_routes {
+            Some(routes) => Some(self.validate_channel_routes(user_id, routes).await?),
+            None => None,
+        };
+        if channel_routes.is_some() {
+            param_count += 1;
+            updates.push(format!("channel_routes = ${}", param_count));
+        }
+        if update.digest_frequency.is_some() {
+            param_count += 1;
+            updates.push(format!("digest_frequency = ${}", param_count));
+        }
+        if update.digest_hour.is_some() {
+            param_count += 1;
+            updates.push(format!("digest_hour = ${}", param_count));
+        }
+        if update.digest_weekday.is_some() {
+            param_count += 1;
+            updates.push(format!("digest_weekday = ${}", param_count));
+        }
+        if update.quiet_hours_enabled.is_some() {
+            param_count += 1;
+            updates.push(format!("quiet_hours_enabled = ${}", param_count));
+        }
+        if update.quiet_hours_start.is_some() {
+            param_count += 1;
+            updates.push(format!("quiet_hours_start = ${}", param_count));
+        }
+        if update.quiet_hours_end.is_some() {
+            param_count += 1;
+            updates.push(format!("quiet_hours_end = ${}", param_count));
+        }
+        if update.timezone.is_some() {
+            param_count += 1;
+            updates.push(format!("timezone = ${}", param_count));
+        }
 
         if updates.is_empty() {
             return self.get_user_preferences(user_id).await;
@@ -286,14 +586,105 @@ impl NotificationService {
         if let Some(val) = update.in_app_notifications_enabled {
             query_builder = query_builder.bind(val);
         }
+        if let Some(val) = channel_routes {
+            query_builder = query_builder.bind(val);
+        }
+        if let Some(val) = update.digest_frequency {
+            query_builder = query_builder.bind(val.as_str());
+        }
+        if let Some(val) = update.digest_hour {
+            query_builder = query_builder.bind(val);
+        }
+        if let Some(val) = update.digest_weekday {
+            query_builder = query_builder.bind(val);
+        }
+        if let Some(val) = update.quiet_hours_enabled {
+            query_builder = query_builder.bind(val);
+        }
+        if let Some(val) = update.quiet_hours_start {
+            query_builder = query_builder.bind(val);
+        }
+        if let Some(val) = update.quiet_hours_end {
+            query_builder = query_builder.bind(val);
+        }
+        if let Some(val) = update.timezone {
+            query_builder = query_builder.bind(val);
+        }
 
         let updated = query_builder.fetch_one(&self.db_pool).await?;
 
+        // Held notifications were scheduled with the old settings: release them on the
+        // next scheduler run (as one summary when there are several)
+        if reschedule {
+            sqlx::query(
+                "UPDATE alert_notifications SET held_until = NOW() WHERE user_id = $1 AND held_until > NOW()"
+            )
+            .bind(user_id)
+            .execute(&self.db_pool)
+            .await?;
+        }
+
         tracing::info!("Alert preferences updated for user: {}", user_id);
 
         Ok(updated)
     }
 
+    async fn validate_delivery_schedule(&self, update: &UpdateAlertPreferencesRequest) -> Result<()> {
+        let hours = [update.digest_hour, update.quiet_hours_start, update.quiet_hours_end];
+        if hours.iter().flatten().any(|h| !(0..=23).contains(h)) {
+            return Err(AppError::BadRequest("Hours must be between 0 and 23".to_string()));
+        }
+        if update.digest_weekday.map_or(false, |d| !(1..=7).contains(&d)) {
+            return Err(AppError::BadRequest(
+                "digest_weekday must be between 1 (Monday) and 7 (Sunday)".to_string(),
+            ));
+        }
+        if let Some(timezone) = &update.timezone {
+            let known: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM pg_timezone_names WHERE name = $1)")
+                .bind(timezone)
+                .fetch_one(&self.db_pool)
+                .await?;
+            if !known {
+                return Err(AppError::BadRequest(format!("Unknown timezone: {}", timezone)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that routed channels belong to the user; returns the routes as stored
+    async fn validate_channel_routes(
+        &self,
+        user_id: Uuid,
+        routes: HashMap<AlertType, Vec<Uuid>>,
+    ) -> Result<serde_json::Value> {
+        let mut channel_ids: Vec<Uuid> = routes.values().flatten().copied().collect();
+        channel_ids.sort();
+        channel_ids.dedup();
+
+        let owned: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM alert_channels WHERE user_id = $1 AND id = ANY($2)"
+        )
+        .bind(user_id)
+        .bind(&channel_ids)
+        .fetch_one(&self.db_pool)
+        .await?;
+        if owned != channel_ids.len() as i64 {
+            return Err(AppError::BadRequest("Unknown alert channel in channel_routes".to_string()));
+        }
+
+        let routes: serde_json::Map<String, serde_json::Value> = routes
+            .into_iter()
+            .filter(|(_, ids)| !ids.is_empty())
+            .map(|(alert_type, mut ids)| {
+                ids.sort();
+                ids.dedup();
+                (alert_type.as_str().to_string(), serde_json::json!(ids))
+            })
+            .collect();
+
+        Ok(serde_json::Value::Object(routes))
+    }
+
     // ========================================================================
     // MARKETPLACE WATCHLIST
     // ========================================================================
@@ -307,15 +698,16 @@ impl NotificationService {
         let watchlist = sqlx::query_as!(
             MarketplaceWatchlist,
             r#"
-            INSERT INTO marketplace_watchlist (user_id, name, description, search_criteria, alert_enabled)
-            VALUES ($1, $2, $3, $4, $5)
+            INSERT INTO marketplace_watchlist (user_id, name, description, search_criteria, alert_enabled, check_frequency)
+            VALUES ($1, $2, $3, $4, $5, $6)
             RETURNING *
             "#,
             user_id,
             request.name,
             request.description,
             request.search_criteria,
-            request.alert_enabled.unwrap_or(true)
+            request.alert_enabled.unwrap_or(true),
+            request.check_frequency.unwrap_or(WatchlistCheckFrequency::Hourly).as_str()
         )
         .fetch_one(&self.db_pool)
         .await?;
@@ -382,6 +774,19 @@ impl NotificationService {
             param_count += 1;
             updates.push(format!("alert_enabled = ${}", param_count));
         }
+        if update.check_frequency.is_some() {
+            // A shorter cadence takes effect right away instead of after the pending check
+            param_count += 2;
+            updates.push(format!("check_frequency = ${}", param_count - 1));
+            updates.push(format!(
+                "next_check_at = LEAST(next_check_at, NOW() + make_interval(secs => ${}))",
+                param_count
+            ));
+        }
+
+        if let Some(paused) = update.paused {
+            self.set_watchlist_paused(watchlist_id, user_id, paused).await?;
+        }
 
         if updates.is_empty() {
             return self.get_watchlist(watchlist_id, user_id).await;
@@ -407,6 +812,11 @@ impl NotificationService {
         if let Some(val) = update.alert_enabled {
             query_builder = query_builder.bind(val);
         }
+        if let Some(val) = update.check_frequency {
+            query_builder = query_builder
+                .bind(val.as_str())
+                .bind(val.interval().num_seconds() as f64);
+        }
 
         let updated = query_builder.fetch_one(&self.db_pool).await?;
 
@@ -415,6 +825,39 @@ impl NotificationService {
         Ok(updated)
     }
 
+    /// Pause or resume evaluation of a watchlist and its triggers. Resuming makes it due
+    /// immediately.
+    pub async fn set_watchlist_paused(
+        &self,
+        watchlist_id: Uuid,
+        user_id: Uuid,
+        paused: bool,
+    ) -> Result<MarketplaceWatchlist> {
+        let watchlist = sqlx::query_as::<_, MarketplaceWatchlist>(
+            r#"
+            UPDATE marketplace_watchlist
+            SET paused_at = CASE WHEN $3 THEN COALESCE(paused_at, NOW()) ELSE NULL END,
+                next_check_at = CASE WHEN NOT $3 AND paused_at IS NOT NULL THEN NOW() ELSE next_check_at END
+            WHERE id = $1 AND user_id = $2
+            RETURNING *
+            "#,
+        )
+        .bind(watchlist_id)
+        .bind(user_id)
+        .bind(paused)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Watchlist not found".to_string()))?;
+
+        tracing::info!(
+            "Watchlist {}: {}",
+            if paused { "paused" } else { "resumed" },
+            watchlist_id
+        );
+
+        Ok(watchlist)
+    }
+
     /// Delete a watchlist
     pub async fn delete_watchlist(&self, watchlist_id: Uuid, user_id: Uuid) -> Result<()> {
         let result = sqlx::query!(
@@ -434,26 +877,204 @@ impl NotificationService {
         Ok(())
     }
 
-    /// Update watchlist last checked timestamp and match count
+    /// Update watchlist last checked timestamp and match count, and schedule the next check
     pub async fn update_watchlist_stats(
         &self,
         watchlist_id: Uuid,
         new_matches: i32,
+        next_check_at: DateTime<Utc>,
     ) -> Result<()> {
         sqlx::query!(
             r#"
             UPDATE marketplace_watchlist
             SET last_checked_at = NOW(),
                 last_match_count = $1,
-                total_matches_found = total_matches_found + $1
+                total_matches_found = total_matches_found + $1,
+                next_check_at = $3
             WHERE id = $2
             "#,
             new_matches,
-            watchlist_id
+            watchlist_id,
+            next_check_at
         )
         .execute(&self.db_pool)
         .await?;
 
         Ok(())
     }
+
+    // ========================================================================
+    // WATCHLIST TRIGGERS
+    // ========================================================================
+
+    /// List the triggers of a watchlist
+    pub async fn list_watchlist_triggers(&self, watchlist_id: Uuid, user_id: Uuid) -> Result<Vec<WatchlistTrigger>> {
+        self.get_watchlist(watchlist_id, user_id).await?;
+
+        let triggers = sqlx::query_as::<_, WatchlistTrigger>(
+            "SELECT * FROM watchlist_triggers WHERE watchlist_id = $1 ORDER BY created_at",
+        )
+        .bind(watchlist_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(triggers)
+    }
+
+    /// Add a price drop or back in stock trigger to a watchlist
+    pub async fn create_watchlist_trigger(
+        &self,
+        watchlist_id: Uuid,
+        user_id: Uuid,
+        request: CreateWatchlistTriggerRequest,
+    ) -> Result<WatchlistTrigger> {
+        self.get_watchlist(watchlist_id, user_id).await?;
+
+        let ndc_code = request.ndc_code.trim();
+        if ndc_code.is_empty() || ndc_code.len() > 20 {
+            return Err(AppError::BadRequest("ndc_code must be 1-20 characters".to_string()));
+        }
+        let price_threshold = match request.trigger_type {
+            WatchlistTriggerType::PriceDrop => Some(
+                request
+                    .price_threshold
+                    .ok_or_else(|| AppError::BadRequest("price_threshold is required for price_drop triggers".to_string()))?,
+            ),
+            WatchlistTriggerType::BackInStock => None,
+        };
+        Self::validate_trigger_settings(price_threshold, request.cooldown_hours)?;
+
+        let trigger = sqlx::query_as::<_, WatchlistTrigger>(
+            r#"
+            INSERT INTO watchlist_triggers (watchlist_id, trigger_type, ndc_code, price_threshold, cooldown_hours)
+            VALUES ($1, $2, $3, $4, COALESCE($5, 24))
+            RETURNING *
+            "#,
+        )
+        .bind(watchlist_id)
+        .bind(request.trigger_type.as_str())
+        .bind(ndc_code)
+        .bind(price_threshold)
+        .bind(request.cooldown_hours)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        tracing::info!(
+            "Watchlist trigger created: id={}, watchlist={}, type={}, ndc={}",
+            trigger.id,
+            watchlist_id,
+            trigger.trigger_type,
+            trigger.ndc_code
+        );
+
+        Ok(trigger)
+    }
+
+    /// Update a watchlist trigger
+    pub async fn update_watchlist_trigger(
+        &self,
+        watchlist_id: Uuid,
+        trigger_id: Uuid,
+        user_id: Uuid,
+        request: UpdateWatchlistTriggerRequest,
+    ) -> Result<WatchlistTrigger> {
+        let existing = self.get_watchlist_trigger(watchlist_id, trigger_id, user_id).await?;
+
+        if request.price_threshold.is_some() && existing.trigger_type != WatchlistTriggerType::PriceDrop.as_str() {
+            return Err(AppError::BadRequest("price_threshold only applies to price_drop triggers".to_string()));
+        }
+        Self::validate_trigger_settings(request.price_threshold, request.cooldown_hours)?;
+
+        // A new threshold starts over: the next check alerts if the price is already below it
+        let trigger = sqlx::query_as::<_, WatchlistTrigger>(
+            r#"
+            UPDATE watchlist_triggers
+            SET price_threshold = COALESCE($2, price_threshold),
+                cooldown_hours = COALESCE($3, cooldown_hours),
+                is_active = COALESCE($4, is_active),
+                last_best_price = CASE WHEN $2 IS NULL THEN last_best_price ELSE NULL END,
+                updated_at = NOW()
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(existing.id)
+        .bind(request.price_threshold)
+        .bind(request.cooldown_hours)
+        .bind(request.is_active)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        Ok(trigger)
+    }
+
+    /// Delete a watchlist trigger
+    pub async fn delete_watchlist_trigger(&self, watchlist_id: Uuid, trigger_id: Uuid, user_id: Uuid) -> Result<()> {
+        let existing = self.get_watchlist_trigger(watchlist_id, trigger_id, user_id).await?;
+
+        sqlx::query("DELETE FROM watchlist_triggers WHERE id = $1")
+            .bind(existing.id)
+            .execute(&self.db_pool)
+            .await?;
+
+        tracing::info!("Watchlist trigger deleted: {}", trigger_id);
+
+        Ok(())
+    }
+
+    /// Record what a trigger check observed, and whether it alerted
+    pub async fn record_watchlist_trigger_check(
+        &self,
+        trigger_id: Uuid,
+        best_price: Option<Decimal>,
+        in_stock: bool,
+        triggered_at: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE watchlist_triggers
+            SET last_best_price = $2,
+                last_in_stock = $3,
+                last_evaluated_at = NOW(),
+                last_triggered_at = COALESCE($4, last_triggered_at),
+                trigger_count = trigger_count + CASE WHEN $4 IS NULL THEN 0 ELSE 1 END
+            WHERE id = $1
+            "#,
+        )
+        .bind(trigger_id)
+        .bind(best_price)
+        .bind(in_stock)
+        .bind(triggered_at)
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_watchlist_trigger(&self, watchlist_id: Uuid, trigger_id: Uuid, user_id: Uuid) -> Result<WatchlistTrigger> {
+        sqlx::query_as::<_, WatchlistTrigger>(
+            r#"
+            SELECT t.*
+            FROM watchlist_triggers t
+            JOIN marketplace_watchlist w ON w.id = t.watchlist_id
+            WHERE t.id = $1 AND t.watchlist_id = $2 AND w.user_id = $3
+            "#,
+        )
+        .bind(trigger_id)
+        .bind(watchlist_id)
+        .bind(user_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Watchlist trigger not found".to_string()))
+    }
+
+    fn validate_trigger_settings(price_threshold: Option<Decimal>, cooldown_hours: Option<i32>) -> Result<()> {
+        if price_threshold.map_or(false, |p| p < Decimal::ZERO) {
+            return Err(AppError::BadRequest("price_threshold must not be negative".to_string()));
+        }
+        if cooldown_hours.map_or(false, |h| !(1..=720).contains(&h)) {
+            return Err(AppError::BadRequest("cooldown_hours must be between 1 and 720".to_string()));
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,514 @@
+/// OpenFDA Device (UDI/GUDID) Catalog Service
+///
+/// Syncs https://api.fda.gov/device/udi.json into `openfda_device_catalog` so medical
+/// device listings can be created from a GUDID primary device identifier.
+/// Sync runs are recorded in `openfda_sync_log` with `sync_type = 'device'`.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::{Duration, Instant};
+use chrono::{Days, Months, NaiveDate, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::{
+    middleware::error_handling::{AppError, Result},
+    models::{
+        background_job::{JobType, NewJob, OpenFdaDeviceSyncJob},
+        openfda_device::*,
+    },
+    repositories::OpenFdaRepository,
+    services::{BackgroundJobService, Shutdown, SyncLogTable},
+};
+
+/// OpenFDA rejects skip values above 25000
+const MAX_SKIP: usize = 25_000;
+
+/// GUDID opened in 2013; device records are partitioned by publish month from then on
+const FIRST_PUBLISH_YEAR: i32 = 2013;
+
+pub struct OpenFdaDeviceService {
+    db_pool: PgPool,
+    api_base_url: String,
+    batch_size: usize,
+    batch_delay_ms: u64,
+    max_retries: u32,
+    http_client: reqwest::Client,
+}
+
+impl OpenFdaDeviceService {
+    pub fn new(db_pool: PgPool) -> Self {
+        let http_client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            db_pool,
+            api_base_url: std::env::var("OPENFDA_DEVICE_API_BASE_URL")
+                .unwrap_or_else(|_| "https://api.fda.gov/device/udi.json".to_string()),
+            batch_size: std::env::var("OPENFDA_BATCH_SIZE")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(100),
+            batch_delay_ms: std::env::var("OPENFDA_BATCH_DELAY_MS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(100),
+            max_retries: std::env::var("OPENFDA_MAX_RETRIES")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(3),
+            http_client,
+        }
+    }
+
+    // ========================================================================
+    // SYNC
+    // ========================================================================
+
+    /// Queue a device sync as a background job and return its sync log id
+    pub async fn start_background_sync(&self, limit: Option<usize>) -> Result<Uuid> {
+        let repo = OpenFdaRepository::new(self.db_pool.clone());
+
+        if repo.is_sync_running().await? {
+            return Err(AppError::Conflict);
+        }
+
+        let log_id = repo.start_sync_log_with_type("device", None).await?;
+        let job = OpenFdaDeviceSyncJob { sync_log_id: log_id, limit };
+        let queued = BackgroundJobService::new(self.db_pool.clone())
+            .enqueue(NewJob::new(JobType::OpenFdaDeviceSync, &job).with_dedupe_key(format!("openfda_device_sync:{}", log_id)))
+            .await;
+        if let Err(e) = queued {
+            let _ = repo.fail_sync_log(log_id, "Sync could not be queued").await;
+            return Err(e);
+        }
+
+        tracing::info!("OpenFDA device sync queued with ID: {}", log_id);
+        Ok(log_id)
+    }
+
+    /// Run the sync of a background job; a retry starts the sync over
+    pub async fn run_sync_job(job: &OpenFdaDeviceSyncJob, retry: bool, pool: PgPool) -> Result<()> {
+        let log_id = job.sync_log_id;
+        let repo = OpenFdaRepository::new(pool.clone());
+        let _in_flight = Shutdown::track_sync(SyncLogTable::OpenFda, log_id);
+
+        // The previous attempt failed the sync log or left it in progress when its worker died
+        if retry && !repo.restart_sync_log(log_id).await? {
+            tracing::info!("OpenFDA device sync {} was cancelled or has finished, not retrying it", log_id);
+            return Ok(());
+        }
+
+        let service = OpenFdaDeviceService::new(pool.clone());
+        let start = Instant::now();
+
+        match service.sync_devices(log_id, job.limit).await {
+            Ok((fetched, inserted, updated, skipped)) => {
+                if let Err(e) = repo
+                    .complete_sync_log_full(log_id, fetched, inserted, updated, skipped, 0, start.elapsed().as_millis() as i32)
+                    .await
+                {
+                    tracing::error!("Failed to complete device sync log {}: {:?}", log_id, e);
+                }
+                tracing::info!(
+                    "OpenFDA device sync completed: fetched={}, inserted={}, updated={}, skipped={}",
+                    fetched, inserted, updated, skipped
+                );
+                Ok(())
+            }
+            Err(e) if Shutdown::requested() => {
+                // The job is queued again and starts the sync over
+                tracing::warn!("OpenFDA device sync {} interrupted by shutdown: {:?}", log_id, e);
+                let _ = SyncLogTable::OpenFda.mark_interrupted(&pool, log_id).await;
+                Err(e)
+            }
+            Err(e) => {
+                tracing::error!("OpenFDA device sync {} failed: {:?}", log_id, e);
+                let _ = repo.fail_sync_log(log_id, &format!("{:?}", e)).await;
+                Err(e)
+            }
+        }
+    }
+
+    /// Fetch and upsert device records, one publish_date partition at a time so no query
+    /// skips past MAX_SKIP; a partition with more records is split into weeks, then days.
+    /// Returns (fetched, inserted, updated, skipped).
+    async fn sync_devices(&self, log_id: Uuid, limit: Option<usize>) -> Result<(i32, i32, i32, i32)> {
+        let repo = OpenFdaRepository::new(self.db_pool.clone());
+        let total_limit = limit.unwrap_or(usize::MAX);
+
+        let Some(probe) = self.fetch_batch(None, 0, 1).await? else {
+            return Ok((0, 0, 0, 0));
+        };
+        repo.set_total_expected(log_id, (probe.meta.results.total as usize).min(total_limit) as i32)
+            .await?;
+
+        let mut batch = 0;
+        let (mut fetched, mut inserted, mut updated, mut skipped) = (0i32, 0i32, 0i32, 0i32);
+
+        let mut partitions: VecDeque<DevicePartition> = device_sync_partitions(Utc::now().date_naive()).into();
+
+        while let Some(partition) = partitions.pop_front() {
+            let search = partition.search();
+            let mut skip = 0;
+
+            while (fetched as usize) < total_limit && skip <= MAX_SKIP {
+                if Shutdown::requested() {
+                    return Err(Shutdown::interrupted());
+                }
+
+                let batch_limit = self.batch_size.min(total_limit - fetched as usize);
+                let started = Instant::now();
+                let response = match self.fetch_batch(Some(&search), skip, batch_limit).await? {
+                    Some(response) => response,
+                    None => break, // No more results in this partition
+                };
+                let api_time_ms = started.elapsed().as_millis() as i32;
+
+                if skip == 0 && response.meta.results.total as usize > MAX_SKIP {
+                    if let Some(parts) = partition.split() {
+                        tracing::debug!(
+                            "OpenFDA device partition {} has {} records, splitting it into {}",
+                            partition,
+                            response.meta.results.total,
+                            parts.len()
+                        );
+                        parts.into_iter().rev().for_each(|part| partitions.push_front(part));
+                        break;
+                    }
+                    tracing::warn!(
+                        "OpenFDA device partition {} has {} records; only the first {} can be fetched",
+                        partition,
+                        response.meta.results.total,
+                        MAX_SKIP + batch_limit
+                    );
+                }
+
+                let batch_count = response.results.len();
+                if batch_count == 0 {
+                    break;
+                }
+
+                let entries: Vec<OpenFdaDeviceEntry> = response
+                    .results
+                    .iter()
+                    .filter_map(|r| r.to_device_entry())
+                    .collect();
+                skipped += (batch_count - entries.len()) as i32;
+
+                for entry in &entries {
+                    if self.upsert_device(entry).await? {
+                        inserted += 1;
+                    } else {
+                        updated += 1;
+                    }
+                }
+
+                fetched += batch_count as i32;
+                batch += 1;
+                skip += batch_count;
+
+                repo.update_sync_progress(log_id, fetched, inserted, updated, skipped, 0, batch, api_time_ms)
+                    .await?;
+
+                if batch_count < batch_limit {
+                    break;
+                }
+
+                tokio::time::sleep(Duration::from_millis(self.batch_delay_ms)).await;
+            }
+
+            if fetched as usize >= total_limit {
+                break;
+            }
+        }
+
+        Ok((fetched, inserted, updated, skipped))
+    }
+
+    async fn fetch_batch(&self, search: Option<&str>, skip: usize, limit: usize) -> Result<Option<OpenFdaDeviceApiResponse>> {
+        let url = match search {
+            Some(search) => format!("{}?search={}&limit={}&skip={}", self.api_base_url, search, limit, skip),
+            None => format!("{}?limit={}&skip={}", self.api_base_url, limit, skip),
+        };
+        let mut last_error = None;
+
+        for attempt in 0..self.max_retries {
+            if attempt > 0 {
+                // Exponential backoff: 2s, 4s, ...
+                tokio::time::sleep(Duration::from_secs(1 << attempt)).await;
+            }
+
+            let response = match self.http_client.get(&url).send().await {
+                Ok(response) => response,
+                Err(e) => {
+                    last_error = Some(AppError::Internal(anyhow::anyhow!("HTTP request failed: {}", e)));
+                    continue;
+                }
+            };
+
+            match response.status().as_u16() {
+                // OpenFDA returns 404 when skip is past the end of the result set
+                404 => return Ok(None),
+                429 => {
+                    tracing::warn!("Rate limited by OpenFDA device API, backing off...");
+                    tokio::time::sleep(Duration::from_secs(60)).await;
+                    last_error = Some(AppError::TooManyRequests("OpenFDA rate limit exceeded".to_string()));
+                    continue;
+                }
+                status if !(200..300).contains(&status) => {
+                    last_error = Some(AppError::Internal(anyhow::anyhow!(
+                        "OpenFDA device API returned status: {}", status
+                    )));
+                    continue;
+                }
+                _ => {}
+            }
+
+            match response.json::<OpenFdaDeviceApiResponse>().await {
+                Ok(data) => return Ok(Some(data)),
+                Err(e) => {
+                    last_error = Some(AppError::Internal(anyhow::anyhow!(
+                        "Failed to parse OpenFDA device response: {}", e
+                    )));
+                }
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| {
+            AppError::Internal(anyhow::anyhow!("Failed after {} retries", self.max_retries))
+        }))
+    }
+
+    /// Upsert by primary DI. Returns true when the record was inserted.
+    async fn upsert_device(&self, entry: &OpenFdaDeviceEntry) -> Result<bool> {
+        let inserted: bool = sqlx::query_scalar(
+            r#"
+            INSERT INTO openfda_device_catalog (
+                primary_di, issuing_agency, brand_name, version_model_number, catalog_number,
+                company_name, device_description, device_class, product_codes, gmdn_terms,
+                is_rx, is_otc, is_single_use, is_sterile, mri_safety,
+                commercial_distribution_status, record_status, publish_date, identifiers, last_synced_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, NOW())
+            ON CONFLICT (primary_di) DO UPDATE SET
+                issuing_agency = EXCLUDED.issuing_agency,
+                brand_name = EXCLUDED.brand_name,
+                version_model_number = EXCLUDED.version_model_number,
+                catalog_number = EXCLUDED.catalog_number,
+                company_name = EXCLUDED.company_name,
+                device_description = EXCLUDED.device_description,
+                device_class = EXCLUDED.device_class,
+                product_codes = EXCLUDED.product_codes,
+                gmdn_terms = EXCLUDED.gmdn_terms,
+                is_rx = EXCLUDED.is_rx,
+                is_otc = EXCLUDED.is_otc,
+                is_single_use = EXCLUDED.is_single_use,
+                is_sterile = EXCLUDED.is_sterile,
+                mri_safety = EXCLUDED.mri_safety,
+                commercial_distribution_status = EXCLUDED.commercial_distribution_status,
+                record_status = EXCLUDED.record_status,
+                publish_date = EXCLUDED.publish_date,
+                identifiers = EXCLUDED.identifiers,
+                last_synced_at = NOW(),
+                updated_at = NOW()
+            RETURNING (xmax = 0) AS inserted
+            "#
+        )
+        .bind(&entry.primary_di)
+        .bind(&entry.issuing_agency)
+        .bind(&entry.brand_name)
+        .bind(&entry.version_model_number)
+        .bind(&entry.catalog_number)
+        .bind(&entry.company_name)
+        .bind(&entry.device_description)
+        .bind(&entry.device_class)
+        .bind(&entry.product_codes)
+        .bind(&entry.gmdn_terms)
+        .bind(entry.is_rx)
+        .bind(entry.is_otc)
+        .bind(entry.is_single_use)
+        .bind(entry.is_sterile)
+        .bind(&entry.mri_safety)
+        .bind(&entry.commercial_distribution_status)
+        .bind(&entry.record_status)
+        .bind(entry.publish_date)
+        .bind(&entry.identifiers)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        Ok(inserted)
+    }
+
+    // ========================================================================
+    // LOOKUP
+    // ========================================================================
+
+    pub async fn search(&self, request: DeviceSearchRequest) -> Result<Vec<OpenFdaDeviceEntry>> {
+        let limit = request.limit.unwrap_or(20).clamp(1, 100);
+        let offset = request.offset.unwrap_or(0).max(0);
+
+        let devices = sqlx::query_as::<_, OpenFdaDeviceEntry>(
+            r#"
+            SELECT * FROM openfda_device_catalog
+            WHERE ($1::TEXT IS NULL
+                   OR brand_name ILIKE $1
+                   OR device_description ILIKE $1
+                   OR version_model_number ILIKE $1
+                   OR primary_di = $2)
+              AND ($3::TEXT IS NULL OR company_name ILIKE $3)
+              AND ($4::TEXT IS NULL OR device_class = $4)
+            ORDER BY brand_name ASC NULLS LAST
+            LIMIT $5 OFFSET $6
+            "#
+        )
+        .bind(request.query.as_ref().map(|q| format!("%{}%", q.trim())))
+        .bind(request.query.as_deref().map(str::trim))
+        .bind(request.company_name.as_ref().map(|c| format!("%{}%", c.trim())))
+        .bind(request.device_class.as_deref().map(|c| c.trim().to_uppercase()))
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(devices)
+    }
+
+    pub async fn get_by_primary_di(&self, primary_di: &str) -> Result<Option<OpenFdaDeviceEntry>> {
+        let device = sqlx::query_as::<_, OpenFdaDeviceEntry>(
+            "SELECT * FROM openfda_device_catalog WHERE primary_di = $1"
+        )
+        .bind(primary_di.trim())
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        Ok(device)
+    }
+}
+
+/// A slice of the device dataset that is paged through on its own
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DevicePartition {
+    /// Records without a publish date
+    MissingPublishDate,
+    /// Records published from the first through the second date
+    Published(NaiveDate, NaiveDate),
+}
+
+impl DevicePartition {
+    /// OpenFDA search filter of the partition
+    pub fn search(&self) -> String {
+        match self {
+            DevicePartition::MissingPublishDate => "_missing_:publish_date".to_string(),
+            DevicePartition::Published(from, to) => {
+                format!("publish_date:[{}+TO+{}]", from.format("%Y%m%d"), to.format("%Y%m%d"))
+            }
+        }
+    }
+
+    /// Partitions covering the same records in smaller ranges: weeks of a longer range,
+    /// days of a week. None for a single day or records without a publish date.
+    pub fn split(&self) -> Option<Vec<DevicePartition>> {
+        let DevicePartition::Published(from, to) = *self else {
+            return None;
+        };
+        let step = match (to - from).num_days() + 1 {
+            days if days > 7 => 7,
+            days if days > 1 => 1,
+            _ => return None,
+        };
+
+        let mut parts = Vec::new();
+        let mut start = from;
+        while start <= to {
+            let end = start.checked_add_days(Days::new(step - 1)).map_or(to, |end| end.min(to));
+            parts.push(DevicePartition::Published(start, end));
+            let Some(next) = end.succ_opt() else { break };
+            start = next;
+        }
+        Some(parts)
+    }
+}
+
+impl fmt::Display for DevicePartition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.search())
+    }
+}
+
+/// Partitions that together cover the device dataset: one publish_date range per month
+/// up to `today`, plus records without a publish date. sync_devices splits a month
+/// further when it holds more than MAX_SKIP records.
+pub fn device_sync_partitions(today: NaiveDate) -> Vec<DevicePartition> {
+    let mut partitions = vec![DevicePartition::MissingPublishDate];
+    let mut month = NaiveDate::from_ymd_opt(FIRST_PUBLISH_YEAR, 1, 1).unwrap_or(today);
+
+    while month <= today {
+        let Some(next) = month.checked_add_months(Months::new(1)) else { break };
+        let last_day = next.pred_opt().unwrap_or(month);
+        partitions.push(DevicePartition::Published(month, last_day));
+        month = next;
+    }
+    partitions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_device_sync_partitions_cover_every_month() {
+        let today = NaiveDate::from_ymd_opt(2014, 3, 10).unwrap();
+        let partitions: Vec<String> = device_sync_partitions(today).iter().map(DevicePartition::search).collect();
+
+        assert_eq!(
+            partitions,
+            vec![
+                "_missing_:publish_date",
+                "publish_date:[20130101+TO+20130131]",
+                "publish_date:[20130201+TO+20130228]",
+                "publish_date:[20130301+TO+20130331]",
+                "publish_date:[20130401+TO+20130430]",
+                "publish_date:[20130501+TO+20130531]",
+                "publish_date:[20130601+TO+20130630]",
+                "publish_date:[20130701+TO+20130731]",
+                "publish_date:[20130801+TO+20130831]",
+                "publish_date:[20130901+TO+20130930]",
+                "publish_date:[20131001+TO+20131031]",
+                "publish_date:[20131101+TO+20131130]",
+                "publish_date:[20131201+TO+20131231]",
+                "publish_date:[20140101+TO+20140131]",
+                "publish_date:[20140201+TO+20140228]",
+                "publish_date:[20140301+TO+20140331]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_device_partition_splits_into_weeks_then_days() {
+        let date = |day| NaiveDate::from_ymd_opt(2024, 2, day).unwrap();
+        let month = DevicePartition::Published(date(1), date(29));
+
+        let weeks = month.split().unwrap();
+        assert_eq!(
+            weeks,
+            vec![
+                DevicePartition::Published(date(1), date(7)),
+                DevicePartition::Published(date(8), date(14)),
+                DevicePartition::Published(date(15), date(21)),
+                DevicePartition::Published(date(22), date(28)),
+                DevicePartition::Published(date(29), date(29)),
+            ]
+        );
+
+        let days = weeks[0].split().unwrap();
+        assert_eq!(days.len(), 7);
+        assert_eq!(days[0], DevicePartition::Published(date(1), date(1)));
+        assert_eq!(days[6].search(), "publish_date:[20240207+TO+20240207]");
+
+        assert_eq!(days[0].split(), None);
+        assert_eq!(weeks[4].split(), None);
+        assert_eq!(DevicePartition::MissingPublishDate.split(), None);
+    }
+}
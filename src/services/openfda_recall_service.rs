@@ -0,0 +1,379 @@
+/// OpenFDA Drug Enforcement (Recall) Service
+///
+/// Syncs https://api.fda.gov/drug/enforcement.json into `openfda_recalls`, linked to
+/// product NDCs so recalls can be matched against the catalog and inventory.
+/// Sync runs are recorded in `openfda_sync_log` with `sync_type = 'recall'`; after the
+/// first run, syncs only fetch reports newer than the latest stored report date.
+
+use std::time::{Duration, Instant};
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::{
+    middleware::error_handling::{AppError, Result},
+    models::{
+        background_job::{JobType, NewJob, OpenFdaRecallSyncJob},
+        openfda_recall::*,
+    },
+    repositories::{OpenFdaRecallRepository, OpenFdaRepository},
+    services::{BackgroundJobService, Shutdown, SyncLogTable},
+    utils::ndc::hyphenated_ndcs,
+};
+
+/// OpenFDA rejects skip values above 25000
+const MAX_SKIP: usize = 25_000;
+
+/// Incremental syncs re-read this many days before the latest stored report date, since
+/// reports are sometimes published or reclassified late
+const REPORT_DATE_OVERLAP_DAYS: i64 = 30;
+
+pub struct OpenFdaRecallService {
+    db_pool: PgPool,
+    api_base_url: String,
+    batch_size: usize,
+    batch_delay_ms: u64,
+    max_retries: u32,
+    http_client: reqwest::Client,
+}
+
+impl OpenFdaRecallService {
+    pub fn new(db_pool: PgPool) -> Self {
+        let http_client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            db_pool,
+            api_base_url: std::env::var("OPENFDA_ENFORCEMENT_API_BASE_URL")
+                .unwrap_or_else(|_| "https://api.fda.gov/drug/enforcement.json".to_string()),
+            batch_size: std::env::var("OPENFDA_BATCH_SIZE")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(100),
+            batch_delay_ms: std::env::var("OPENFDA_BATCH_DELAY_MS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(100),
+            max_retries: std::env::var("OPENFDA_MAX_RETRIES")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(3),
+            http_client,
+        }
+    }
+
+    // ========================================================================
+    // SYNC
+    // ========================================================================
+
+    /// Queue a recall sync as a background job and return its sync log id.
+    /// `full = true` ignores previously stored reports and re-reads the whole dataset.
+    pub async fn start_background_sync(&self, full: bool) -> Result<Uuid> {
+        let repo = OpenFdaRepository::new(self.db_pool.clone());
+
+        if repo.is_sync_running().await? {
+            return Err(AppError::Conflict);
+        }
+
+        let log_id = repo.start_sync_log_with_type("recall", None).await?;
+        let job = OpenFdaRecallSyncJob { sync_log_id: log_id, full };
+        let queued = BackgroundJobService::new(self.db_pool.clone())
+            .enqueue(NewJob::new(JobType::OpenFdaRecallSync, &job).with_dedupe_key(format!("openfda_recall_sync:{}", log_id)))
+            .await;
+        if let Err(e) = queued {
+            let _ = repo.fail_sync_log(log_id, "Sync could not be queued").await;
+            return Err(e);
+        }
+
+        tracing::info!("OpenFDA recall sync queued with ID: {}", log_id);
+        Ok(log_id)
+    }
+
+    /// Run the sync of a background job; a retry starts the sync over
+    pub async fn run_sync_job(job: &OpenFdaRecallSyncJob, retry: bool, pool: PgPool) -> Result<()> {
+        let log_id = job.sync_log_id;
+        let repo = OpenFdaRepository::new(pool.clone());
+        let _in_flight = Shutdown::track_sync(SyncLogTable::OpenFda, log_id);
+
+        // The previous attempt failed the sync log or left it in progress when its worker died
+        if retry && !repo.restart_sync_log(log_id).await? {
+            tracing::info!("OpenFDA recall sync {} was cancelled or has finished, not retrying it", log_id);
+            return Ok(());
+        }
+
+        let service = OpenFdaRecallService::new(pool.clone());
+        let start = Instant::now();
+
+        match service.sync_recalls(log_id, job.full).await {
+            Ok((fetched, inserted, updated, skipped)) => {
+                if let Err(e) = repo
+                    .complete_sync_log_full(log_id, fetched, inserted, updated, skipped, 0, start.elapsed().as_millis() as i32)
+                    .await
+                {
+                    tracing::error!("Failed to complete recall sync log {}: {:?}", log_id, e);
+                }
+                tracing::info!(
+                    "OpenFDA recall sync completed: fetched={}, inserted={}, updated={}, skipped={}",
+                    fetched, inserted, updated, skipped
+                );
+                Ok(())
+            }
+            Err(e) if Shutdown::requested() => {
+                // The job is queued again and starts the sync over
+                tracing::warn!("OpenFDA recall sync {} interrupted by shutdown: {:?}", log_id, e);
+                let _ = SyncLogTable::OpenFda.mark_interrupted(&pool, log_id).await;
+                Err(e)
+            }
+            Err(e) => {
+                tracing::error!("OpenFDA recall sync {} failed: {:?}", log_id, e);
+                let _ = repo.fail_sync_log(log_id, &format!("{:?}", e)).await;
+                Err(e)
+            }
+        }
+    }
+
+    /// Fetch and upsert enforcement reports. Returns (fetched, inserted, updated, skipped).
+    async fn sync_recalls(&self, log_id: Uuid, full: bool) -> Result<(i32, i32, i32, i32)> {
+        let repo = OpenFdaRepository::new(self.db_pool.clone());
+        let recall_repo = OpenFdaRecallRepository::new(self.db_pool.clone());
+
+        let search = if full {
+            None
+        } else {
+            recall_repo.latest_report_date().await?.map(|latest| {
+                let since = latest - chrono::Duration::days(REPORT_DATE_OVERLAP_DAYS);
+                let today = chrono::Utc::now().date_naive();
+                format!("report_date:[{}+TO+{}]", since.format("%Y%m%d"), today.format("%Y%m%d"))
+            })
+        };
+        tracing::info!(
+            "Starting OpenFDA recall sync (log_id: {}, filter: {})",
+            log_id,
+            search.as_deref().unwrap_or("none")
+        );
+
+        let mut skip = 0;
+        let mut batch = 0;
+        let (mut fetched, mut inserted, mut updated, mut skipped) = (0i32, 0i32, 0i32, 0i32);
+
+        while skip <= MAX_SKIP {
+            if Shutdown::requested() {
+                return Err(Shutdown::interrupted());
+            }
+
+            let started = Instant::now();
+            let response = match self.fetch_batch(search.as_deref(), skip, self.batch_size).await? {
+                Some(response) => response,
+                None => break, // No more results
+            };
+            let api_time_ms = started.elapsed().as_millis() as i32;
+
+            if batch == 0 {
+                let total = (response.meta.results.total as usize).min(MAX_SKIP + self.batch_size) as i32;
+                repo.set_total_expected(log_id, total).await?;
+            }
+
+            let batch_count = response.results.len();
+            if batch_count == 0 {
+                break;
+            }
+
+            for record in &response.results {
+                match record.to_recall() {
+                    Some(recall) => {
+                        if recall_repo.upsert(&recall).await? {
+                            inserted += 1;
+                        } else {
+                            updated += 1;
+                        }
+                    }
+                    None => skipped += 1,
+                }
+            }
+
+            fetched += batch_count as i32;
+            batch += 1;
+            skip += batch_count;
+
+            repo.update_sync_progress(log_id, fetched, inserted, updated, skipped, 0, batch, api_time_ms)
+                .await?;
+
+            if batch_count < self.batch_size {
+                break;
+            }
+
+            tokio::time::sleep(Duration::from_millis(self.batch_delay_ms)).await;
+        }
+
+        Ok((fetched, inserted, updated, skipped))
+    }
+
+    async fn fetch_batch(
+        &self,
+        search: Option<&str>,
+        skip: usize,
+        limit: usize,
+    ) -> Result<Option<OpenFdaEnforcementApiResponse>> {
+        let url = match search {
+            Some(search) => format!("{}?search={}&limit={}&skip={}", self.api_base_url, search, limit, skip),
+            None => format!("{}?limit={}&skip={}", self.api_base_url, limit, skip),
+        };
+        let mut last_error = None;
+
+        for attempt in 0..self.max_retries {
+            if attempt > 0 {
+                // Exponential backoff: 2s, 4s, ...
+                tokio::time::sleep(Duration::from_secs(1 << attempt)).await;
+            }
+
+            let response = match self.http_client.get(&url).send().await {
+                Ok(response) => response,
+                Err(e) => {
+                    last_error = Some(AppError::Internal(anyhow::anyhow!("HTTP request failed: {}", e)));
+                    continue;
+                }
+            };
+
+            match response.status().as_u16() {
+                // OpenFDA returns 404 when nothing matches or skip is past the end
+                404 => return Ok(None),
+                429 => {
+                    tracing::warn!("Rate limited by OpenFDA enforcement API, backing off...");
+                    tokio::time::sleep(Duration::from_secs(60)).await;
+                    last_error = Some(AppError::TooManyRequests("OpenFDA rate limit exceeded".to_string()));
+                    continue;
+                }
+                status if !(200..300).contains(&status) => {
+                    last_error = Some(AppError::Internal(anyhow::anyhow!(
+                        "OpenFDA enforcement API returned status: {}", status
+                    )));
+                    continue;
+                }
+                _ => {}
+            }
+
+            match response.json::<OpenFdaEnforcementApiResponse>().await {
+                Ok(data) => return Ok(Some(data)),
+                Err(e) => {
+                    last_error = Some(AppError::Internal(anyhow::anyhow!(
+                        "Failed to parse OpenFDA enforcement response: {}", e
+                    )));
+                }
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| {
+            AppError::Internal(anyhow::anyhow!("Failed after {} retries", self.max_retries))
+        }))
+    }
+
+    // ========================================================================
+    // LOOKUP
+    // ========================================================================
+
+    pub async fn search(&self, request: RecallSearchRequest) -> Result<Vec<OpenFdaRecall>> {
+        if let Some(classification) = request.classification.as_deref() {
+            if normalize_classification(classification).is_none() {
+                return Err(AppError::BadRequest(format!(
+                    "Unknown recall classification '{}'. Use Class I, II or III",
+                    classification
+                )));
+            }
+        }
+
+        OpenFdaRecallRepository::new(self.db_pool.clone()).search(&request).await
+    }
+
+    /// A recall with the catalog entries its NDCs resolve to
+    pub async fn get_recall(&self, recall_number: &str) -> Result<RecallDetailResponse> {
+        let repo = OpenFdaRecallRepository::new(self.db_pool.clone());
+        let recall = repo
+            .find_by_recall_number(recall_number)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Recall {} not found", recall_number)))?;
+        let catalog_matches = repo.catalog_matches(&recall).await?;
+
+        Ok(RecallDetailResponse { recall, catalog_matches })
+    }
+
+    /// Recalls affecting a product or package NDC, hyphenated or as 10/11 plain digits.
+    /// Plain digits can stand for several layouts; the one the OpenFDA catalog knows is
+    /// used, so recalls of an unrelated product sharing the digits are never returned.
+    pub async fn recalls_for_ndc(&self, ndc: &str) -> Result<Vec<OpenFdaRecall>> {
+        // Package NDCs (labeler-product-package) are matched by their product NDC
+        let mut product_ndcs: Vec<String> = hyphenated_ndcs(ndc)
+            .iter()
+            .filter_map(|candidate| extract_ndcs(candidate).into_iter().next())
+            .map(|(product_ndc, _)| product_ndc)
+            .collect();
+        product_ndcs.sort();
+        product_ndcs.dedup();
+        if product_ndcs.is_empty() {
+            return Err(AppError::BadRequest(format!("Invalid NDC: {}", ndc)));
+        }
+
+        let repo = OpenFdaRecallRepository::new(self.db_pool.clone());
+        if product_ndcs.len() > 1 {
+            product_ndcs = repo.catalog_product_ndcs(&product_ndcs).await?;
+            if product_ndcs.len() != 1 {
+                return Err(AppError::BadRequest(format!(
+                    "NDC {} is ambiguous without hyphens; use the hyphenated NDC",
+                    ndc
+                )));
+            }
+        }
+
+        repo.find_by_product_ndcs(&product_ndcs).await
+    }
+}
+
+// ============================================================================
+// SCHEDULER
+// ============================================================================
+
+/// Background incremental recall sync (FDA publishes enforcement reports weekly)
+pub struct OpenFdaRecallSyncScheduler {
+    pool: PgPool,
+    interval_hours: u64,
+}
+
+impl OpenFdaRecallSyncScheduler {
+    pub fn new(pool: PgPool) -> Self {
+        let interval_hours = std::env::var("OPENFDA_RECALL_SYNC_INTERVAL_HOURS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(24);
+
+        Self { pool, interval_hours }
+    }
+
+    /// Run the scheduler loop
+    pub async fn run(&self) {
+        let mut ticker = tokio::time::interval(Duration::from_secs(self.interval_hours * 3600));
+
+        // Skip first tick (runs immediately on start)
+        ticker.tick().await;
+
+        tracing::info!(
+            "OpenFDA recall sync scheduler started - syncing every {} hours",
+            self.interval_hours
+        );
+
+        loop {
+            ticker.tick().await;
+            if crate::services::MaintenanceService::pauses("openfda_recalls")
+                || !crate::services::SchedulerLeader::leads("openfda_recalls").await
+            {
+                continue;
+            }
+
+            match OpenFdaRecallService::new(self.pool.clone()).start_background_sync(false).await {
+                Ok(sync_id) => tracing::info!("Scheduled OpenFDA recall sync started with ID: {}", sync_id),
+                Err(AppError::Conflict) => {
+                    tracing::info!("OpenFDA sync already in progress, skipping scheduled recall sync");
+                }
+                Err(e) => tracing::error!("Failed to start scheduled OpenFDA recall sync: {:?}", e),
+            }
+        }
+    }
+}
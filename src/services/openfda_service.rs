@@ -1,14 +1,35 @@
 use uuid::Uuid;
+use std::collections::{HashMap, HashSet};
+use futures::{StreamExt, TryStreamExt};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 use sqlx::PgPool;
 use crate::models::openfda::{
-    OpenFdaApiResponse, OpenFdaCatalogEntry, OpenFdaCatalogResponse,
-    OpenFdaSearchRequest, OpenFdaSyncLog, SyncProgressResponse
+    CatalogHistoryResponse, DeltaCheckpoint, OpenFdaApiResponse, OpenFdaCatalogEntry, OpenFdaCatalogResponse,
+    OpenFdaSearchRequest, OpenFdaSyncLog, PartitionCheckpoint, SyncProgressResponse, SyncResumeState, SyncTotals
 };
+use crate::models::sync_preview::{SyncPreview, DEFAULT_PREVIEW_LIMIT, MAX_PREVIEW_LIMIT};
 use crate::repositories::OpenFdaRepository;
-use crate::middleware::error_handling::{Result, AppError};
+use crate::models::sync_anomaly::SyncRunStats;
+use crate::models::sync_source::SourceRuntimeSettings;
+use crate::models::background_job::{JobType, NewJob, OpenFdaSyncJob};
+use crate::models::search_engine::{FacetedSearchResponse, SearchIndex};
+use crate::services::search_engine::{self, SearchEngine};
+use crate::services::{BackgroundJobService, CatalogSource, CatalogSubscriptionService, QueryCache, QueryCacheKind, Shutdown, SyncAnomalyService, SyncLogTable, SyncSourceService};
+use crate::middleware::{error_handling::{Result, AppError}, metrics};
+use crate::utils::{BloomFilter, RequestBudget};
+use utoipa::ToSchema;
+
+/// Catalog fields compared by a dry-run sync (ids and sync timestamps always differ)
+const PREVIEW_FIELDS: &[&str] = &[
+    "brand_name", "generic_name", "labeler_name", "dosage_form", "route", "strength",
+    "active_ingredients", "product_type", "marketing_category", "pharm_class", "dea_schedule",
+    "packaging", "finished", "marketing_start_date", "listing_expiration_date",
+];
+
+/// Detail of the 409 returned when starting or resuming while another sync runs
+const SYNC_IN_PROGRESS: &str = "An OpenFDA sync is already in progress";
 
 /// Configuration for OpenFDA sync
 #[derive(Debug, Clone)]
@@ -19,6 +40,11 @@ pub struct OpenFdaSyncConfig {
     pub max_retries: u32,
     pub request_timeout_secs: u64,
     pub sync_limit: Option<usize>, // None = unlimited (full sync)
+    pub delta_max_age_days: i64,   // Delta syncs fall back to full beyond this checkpoint age
+    pub max_workers: usize,        // Partitions fetched concurrently
+    pub requests_per_minute: u32,  // Request budget shared by all workers
+    pub burst_limit: u32,          // Requests an idle budget may send at once
+    pub api_key: Option<String>,   // Raises OpenFDA's daily and per-minute limits
 }
 
 impl Default for OpenFdaSyncConfig {
@@ -46,6 +72,40 @@ impl Default for OpenFdaSyncConfig {
                 .ok()
                 .and_then(|s| s.parse().ok())
                 .filter(|&v| v > 0), // 0 or unset = unlimited
+            delta_max_age_days: std::env::var("OPENFDA_DELTA_MAX_AGE_DAYS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(30),
+            max_workers: std::env::var("OPENFDA_SYNC_WORKERS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(4),
+            requests_per_minute: std::env::var("OPENFDA_REQUESTS_PER_MINUTE")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(240), // OpenFDA's per-key limit
+            burst_limit: std::env::var("OPENFDA_BURST_LIMIT")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(1),
+            api_key: std::env::var("OPENFDA_API_KEY")
+                .ok()
+                .filter(|k| !k.is_empty()),
+        }
+    }
+}
+
+impl OpenFdaSyncConfig {
+    /// Override the environment with the admin-edited source settings
+    pub fn apply_source_settings(&mut self, settings: &SourceRuntimeSettings) {
+        if let Some(requests_per_minute) = settings.requests_per_minute {
+            self.requests_per_minute = requests_per_minute;
+        }
+        if let Some(burst_limit) = settings.burst_limit {
+            self.burst_limit = burst_limit;
+        }
+        if let Some(api_key) = &settings.api_key {
+            self.api_key = Some(api_key.clone());
         }
     }
 }
@@ -65,11 +125,34 @@ impl Default for SyncState {
     }
 }
 
+/// Persist the resume position every N batches (and at every partition boundary)
+const RESUME_CHECKPOINT_BATCHES: i32 = 10;
+
+/// Sync progress shared by concurrent partition workers
+struct SharedProgress {
+    plan: SyncResumeState,
+    /// Exact set of NDCs handled by this process; the plan's bloom filter also covers
+    /// what a resumed sync handled before it was interrupted
+    handled_ndcs: HashSet<String>,
+    batches_since_checkpoint: i32,
+}
+
+/// Borrowed context handed to each partition worker
+struct PartitionWorker<'a> {
+    log_id: Uuid,
+    config: &'a OpenFdaSyncConfig,
+    sync_state: &'a Arc<RwLock<SyncState>>,
+    progress: &'a tokio::sync::Mutex<SharedProgress>,
+    subscriptions: &'a CatalogSubscriptionService,
+    sync_started_at: chrono::DateTime<chrono::Utc>,
+}
+
 pub struct OpenFdaService {
     repo: OpenFdaRepository,
     config: OpenFdaSyncConfig,
     http_client: reqwest::Client,
     sync_state: Arc<RwLock<SyncState>>,
+    request_budget: RequestBudget,
 }
 
 impl OpenFdaService {
@@ -85,6 +168,7 @@ impl OpenFdaService {
 
         Self {
             repo,
+            request_budget: RequestBudget::with_burst(config.requests_per_minute, config.burst_limit),
             config,
             http_client,
             sync_state: Arc::new(RwLock::new(SyncState::default())),
@@ -96,6 +180,16 @@ impl OpenFdaService {
         Self::new(OpenFdaRepository::new(pool))
     }
 
+    /// Sync configuration with the current admin-edited source settings applied
+    async fn runtime_config(&self, pool: &PgPool) -> OpenFdaSyncConfig {
+        let mut config = self.config.clone();
+        match SyncSourceService::new(pool.clone()).runtime_settings("openfda").await {
+            Ok(settings) => config.apply_source_settings(&settings),
+            Err(e) => tracing::warn!("Failed to load OpenFDA source settings, using environment: {:?}", e),
+        }
+        config
+    }
+
     /// Check if a sync is currently running
     pub async fn is_sync_running(&self) -> Result<bool> {
         self.repo.is_sync_running().await
@@ -123,38 +217,120 @@ impl OpenFdaService {
         Ok(logs.into_iter().map(Into::into).collect())
     }
 
-    /// Start a full sync in the background
-    /// Returns the sync log ID immediately
+    /// Start a sync in the background (`sync_type = "delta"` runs an incremental sync)
+    /// Returns the sync log ID immediately; the sync runs as a background job
     pub async fn start_background_sync(&self, sync_type: &str, pool: PgPool) -> Result<Uuid> {
         // Check if sync is already running
         if self.repo.is_sync_running().await? {
-            return Err(AppError::BadRequest("A sync is already in progress".to_string()));
+            return Err(AppError::InProgress(SYNC_IN_PROGRESS.to_string()));
         }
 
         // Create sync log
         let log_id = self.repo.start_sync_log_with_type(sync_type, None).await?;
 
-        // Update sync state
+        let job = OpenFdaSyncJob { sync_log_id: log_id, sync_type: sync_type.to_string(), resume: false };
+        if let Err(e) = Self::enqueue_sync_job(&job, pool).await {
+            let _ = self.repo.fail_sync_log(log_id, "Sync could not be queued").await;
+            return Err(e);
+        }
+
+        tracing::info!("OpenFDA background sync queued with ID: {}", log_id);
+        Ok(log_id)
+    }
+
+    /// Resume a failed or interrupted sync from its last persisted checkpoint in the background
+    pub async fn resume_sync(&self, sync_id: Uuid, pool: PgPool) -> Result<Uuid> {
+        let log = self
+            .repo
+            .get_sync_log(sync_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Sync not found".to_string()))?;
+
+        let plan = self.repo.get_resume_state(sync_id).await?.ok_or_else(|| {
+            AppError::BadRequest("Sync has no resume checkpoint".to_string())
+        })?;
+
+        if log.status != "failed" && log.status != "interrupted" && !log.is_stale() {
+            return Err(AppError::BadRequest(format!(
+                "Only failed or interrupted syncs can be resumed (status: {})",
+                log.status
+            )));
+        }
+
+        if self.repo.is_sync_running().await? {
+            return Err(AppError::InProgress(SYNC_IN_PROGRESS.to_string()));
+        }
+
+        if !self.repo.mark_resumed(sync_id).await? {
+            return Err(AppError::BadRequest("Sync can no longer be resumed".to_string()));
+        }
+
+        tracing::info!(
+            "Resuming OpenFDA sync {} ({}/{} partitions done)",
+            sync_id,
+            plan.partitions.iter().filter(|p| p.done).count(),
+            plan.partitions.len()
+        );
+
+        let sync_type = log.sync_type.unwrap_or_else(|| "full".to_string());
+        let job = OpenFdaSyncJob { sync_log_id: sync_id, sync_type, resume: true };
+        if let Err(e) = Self::enqueue_sync_job(&job, pool).await {
+            let _ = self.repo.fail_sync_log(sync_id, "Sync could not be queued").await;
+            return Err(e);
+        }
+
+        Ok(sync_id)
+    }
+
+    async fn enqueue_sync_job(job: &OpenFdaSyncJob, pool: PgPool) -> Result<()> {
+        BackgroundJobService::new(pool)
+            .enqueue(NewJob::new(JobType::OpenFdaSync, job).with_dedupe_key(format!("openfda_sync:{}", job.sync_log_id)))
+            .await?;
+        Ok(())
+    }
+
+    /// Run the sync of a background job. Resumes and retries continue from the sync's
+    /// checkpoint; a retry of a sync that has none starts it over.
+    pub async fn run_sync_job(job: &OpenFdaSyncJob, retry: bool, pool: PgPool) -> Result<()> {
+        let log_id = job.sync_log_id;
+        let repo = OpenFdaRepository::new(pool.clone());
+        let _in_flight = Shutdown::track_sync(SyncLogTable::OpenFda, log_id);
+
+        // The previous attempt failed the sync log or left it in progress when its worker died
+        if retry && !repo.restart_sync_log(log_id).await? {
+            tracing::info!("OpenFDA sync {} was cancelled or has finished, not retrying it", log_id);
+            return Ok(());
+        }
+
+        // The request budget follows the runtime settings
+        let config = Self::from_pool(pool.clone()).runtime_config(&pool).await;
+        let service = OpenFdaService::with_config(repo, config.clone());
         {
-            let mut state = self.sync_state.write().await;
+            let mut state = service.sync_state.write().await;
             state.active_sync_id = Some(log_id);
             state.cancel_requested = false;
         }
+        let sync_state = Arc::clone(&service.sync_state);
 
-        // Clone values for the spawned task
-        let config = self.config.clone();
-        let sync_state = Arc::clone(&self.sync_state);
+        let plan = if job.resume || retry { service.repo.get_resume_state(log_id).await? } else { None };
+        let result = match plan {
+            Some(plan) => service.perform_partitioned_sync(log_id, config, sync_state, plan).await,
+            None if job.sync_type == "delta" => service.perform_delta_sync(log_id, config, sync_state).await,
+            None => service.perform_full_sync(log_id, config, sync_state).await,
+        };
 
-        // Spawn background task
-        tokio::spawn(async move {
-            let service = OpenFdaService::from_pool(pool);
-            if let Err(e) = service.perform_full_sync(log_id, config, sync_state).await {
-                tracing::error!("OpenFDA sync failed: {:?}", e);
+        if let Err(e) = &result {
+            if Shutdown::requested() {
+                // The job is queued again and continues from the checkpoint
+                tracing::warn!("OpenFDA sync {} interrupted by shutdown: {:?}", log_id, e);
+                let _ = SyncLogTable::OpenFda.mark_interrupted(&pool, log_id).await;
+            } else {
+                tracing::error!("OpenFDA sync {} failed: {:?}", log_id, e);
+                // Failed partitioned syncs keep their resume state and can be resumed
+                let _ = service.repo.fail_sync_log(log_id, &format!("{:?}", e)).await;
             }
-        });
-
-        tracing::info!("OpenFDA background sync started with ID: {}", log_id);
-        Ok(log_id)
+        }
+        result
     }
 
     /// Perform the actual sync (runs in background)
@@ -164,116 +340,396 @@ impl OpenFdaService {
         log_id: Uuid,
         config: OpenFdaSyncConfig,
         sync_state: Arc<RwLock<SyncState>>,
+    ) -> Result<()> {
+        tracing::info!("Starting OpenFDA full sync with alphabetical partitioning (log_id: {})", log_id);
+
+        // First, get total count
+        let initial_response = self.fetch_batch_with_search(&config, Some(log_id), None, 0, 1).await?;
+        tracing::info!("OpenFDA API reports {} total records", initial_response.meta.results.total);
+
+        let plan = Self::new_plan(
+            Self::full_sync_partitions(),
+            initial_response.meta.results.total,
+            initial_response.meta.last_updated,
+            HashMap::new(),
+        );
+
+        self.perform_partitioned_sync(log_id, config, sync_state, plan).await
+    }
+
+    /// Delta sync: compare the dataset against the latest checkpoint and only refetch
+    /// partitions whose record counts changed, plus listings started since the checkpoint.
+    /// Falls back to a full sync when there is no checkpoint or the last full sync is older
+    /// than `delta_max_age_days` (in-place edits don't change counts, so full syncs still run
+    /// periodically; delta runs don't reset that age).
+    async fn perform_delta_sync(
+        &self,
+        log_id: Uuid,
+        config: OpenFdaSyncConfig,
+        sync_state: Arc<RwLock<SyncState>>,
+    ) -> Result<()> {
+        let today = chrono::Utc::now().date_naive();
+
+        let checkpoint = match self.repo.get_latest_checkpoint().await? {
+            Some(checkpoint) if !checkpoint.needs_full_sync(today, config.delta_max_age_days) => checkpoint,
+            stale => {
+                tracing::info!(
+                    "OpenFDA delta checkpoint {} - falling back to full sync (log_id: {})",
+                    if stale.is_some() { "is stale" } else { "not found" },
+                    log_id
+                );
+                self.repo.set_sync_type(log_id, "full").await?;
+                return self.perform_full_sync(log_id, config, sync_state).await;
+            }
+        };
+
+        let initial_response = self.fetch_batch_with_search(&config, Some(log_id), None, 0, 1).await?;
+        let dataset_last_updated = initial_response.meta.last_updated.clone();
+
+        if dataset_last_updated.is_some() && dataset_last_updated == checkpoint.dataset_last_updated {
+            tracing::info!(
+                "OpenFDA dataset unchanged since {} - nothing to sync (log_id: {})",
+                dataset_last_updated.as_deref().unwrap_or("-"),
+                log_id
+            );
+            self.repo.complete_sync_log_full(log_id, 0, 0, 0, 0, 0, 0).await?;
+            self.repo.save_checkpoint(log_id, &DeltaCheckpoint { synced_on: today, ..checkpoint }).await?;
+            self.clear_sync_state(&sync_state).await;
+            return Ok(());
+        }
+
+        // Probe current partition sizes (one limit=1 request each)
+        let mut partition_totals = HashMap::new();
+        let mut partitions = Vec::new();
+        let mut total_expected = 0;
+
+        for search_filter in Self::full_sync_partitions().into_iter().flatten() {
+            let response = self.fetch_batch_with_search(&config, Some(log_id), Some(&search_filter), 0, 1).await?;
+            let total = response.meta.results.total;
+
+            if checkpoint.partition_totals.get(&search_filter) != Some(&total) {
+                total_expected += total;
+                partitions.push(Some(search_filter.clone()));
+            }
+            partition_totals.insert(search_filter, total);
+
+            self.touch_heartbeat(Some(log_id)).await;
+            tokio::time::sleep(Duration::from_millis(config.batch_delay_ms)).await;
+        }
+
+        // Listings started since the checkpoint (a week of overlap covers late publication)
+        let since = checkpoint.synced_on - chrono::Duration::days(7);
+        let recent_filter = format!(
+            "marketing_start_date:[{}+TO+{}]",
+            since.format("%Y%m%d"),
+            today.format("%Y%m%d")
+        );
+        let recent = self.fetch_batch_with_search(&config, Some(log_id), Some(&recent_filter), 0, 1).await?;
+        total_expected += recent.meta.results.total;
+        partitions.push(Some(recent_filter));
+
+        // Probing ~37 partitions takes a while; don't let the run look stale
+        self.touch_heartbeat(Some(log_id)).await;
+
+        tracing::info!(
+            "Starting OpenFDA delta sync: {} changed partition(s), ~{} records (log_id: {})",
+            partitions.len() - 1,
+            total_expected,
+            log_id
+        );
+
+        let plan = Self::new_plan(partitions, total_expected, dataset_last_updated, partition_totals);
+
+        self.perform_partitioned_sync(log_id, config, sync_state, plan).await
+    }
+
+    /// Search partitions used by full syncs
+    /// OpenFDA has a 25000 skip limit, so we partition by brand_name first letter
+    fn full_sync_partitions() -> Vec<Option<String>> {
+        let mut prefixes: Vec<Option<String>> = Vec::new();
+        // First, try without any filter to get small datasets
+        prefixes.push(None);
+        // Then letters A-Z
+        for c in 'a'..='z' {
+            prefixes.push(Some(format!("brand_name:{}*", c)));
+        }
+        // Numbers 0-9
+        for c in '0'..='9' {
+            prefixes.push(Some(format!("brand_name:{}*", c)));
+        }
+        prefixes
+    }
+
+    /// Resume state for a fresh run of the given partitions
+    fn new_plan(
+        partitions: Vec<Option<String>>,
+        total_expected: i64,
+        dataset_last_updated: Option<String>,
+        partition_totals: HashMap<String, i64>,
+    ) -> SyncResumeState {
+        // Headroom over the expected count keeps the false positive rate near 1%
+        let capacity = (total_expected.max(1_000) as usize) * 6 / 5;
+
+        SyncResumeState {
+            partitions: partitions
+                .into_iter()
+                .map(|filter| PartitionCheckpoint { filter, skip: 0, done: false })
+                .collect(),
+            seen_ndcs: BloomFilter::with_capacity(capacity, 0.01),
+            total_expected,
+            dataset_last_updated,
+            partition_totals,
+            totals: SyncTotals::default(),
+        }
+    }
+
+    async fn clear_sync_state(&self, sync_state: &Arc<RwLock<SyncState>>) {
+        let mut state = sync_state.write().await;
+        state.active_sync_id = None;
+        state.cancel_requested = false;
+    }
+
+    /// Fetch and upsert the partitions of a sync plan, then record a delta checkpoint.
+    /// Up to `max_workers` partitions are fetched concurrently, sharing the request budget.
+    /// The plan doubles as the resume state: it is persisted as the run progresses, and a
+    /// resumed run passes in the last persisted state to continue where it stopped.
+    async fn perform_partitioned_sync(
+        &self,
+        log_id: Uuid,
+        config: OpenFdaSyncConfig,
+        sync_state: Arc<RwLock<SyncState>>,
+        plan: SyncResumeState,
     ) -> Result<()> {
         let start_time = Instant::now();
-        let batch_size = config.batch_size;
-        let mut total_fetched: i32 = 0;
-        let mut total_inserted: i32 = 0;
-        let mut total_updated: i32 = 0;
-        let mut total_skipped: i32 = 0;
-        let mut total_failed: i32 = 0;
-        let mut total_api_time_ms: i32 = 0;
-        let mut current_batch: i32 = 0;
-
-        // OpenFDA has a 25000 skip limit, so we partition by brand_name first letter
-        // Include letters A-Z, numbers 0-9, and a catch-all for others
-        let search_prefixes: Vec<Option<String>> = {
-            let mut prefixes: Vec<Option<String>> = Vec::new();
-            // First, try without any filter to get small datasets
-            prefixes.push(None);
-            // Then letters A-Z
-            for c in 'a'..='z' {
-                prefixes.push(Some(format!("brand_name:{}*", c)));
-            }
-            // Numbers 0-9
-            for c in '0'..='9' {
-                prefixes.push(Some(format!("brand_name:{}*", c)));
-            }
-            prefixes
+        let subscriptions = CatalogSubscriptionService::new(self.repo.pool().clone());
+
+        let sync_log = self
+            .repo
+            .get_sync_log(log_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Sync log not found".to_string()))?;
+        // Records upserted by this run have last_synced_at >= the run's start
+        let sync_started_at = sync_log.sync_started_at;
+        let incremental = sync_log.sync_type.as_deref() == Some("delta");
+        let catalog_size = self.repo.get_total_count().await?;
+
+        self.repo.set_total_expected(log_id, plan.total_expected as i32).await?;
+        self.repo.save_resume_state(log_id, &plan).await?;
+
+        let pending: Vec<usize> = plan
+            .partitions
+            .iter()
+            .enumerate()
+            .filter(|(_, partition)| !partition.done)
+            .map(|(index, _)| index)
+            .collect();
+        let workers = config.max_workers.max(1);
+        tracing::info!(
+            "Fetching {} OpenFDA partition(s) with {} worker(s) (log_id: {})",
+            pending.len(), workers, log_id
+        );
+
+        let progress = tokio::sync::Mutex::new(SharedProgress {
+            plan,
+            handled_ndcs: HashSet::new(),
+            batches_since_checkpoint: 0,
+        });
+        let worker = &PartitionWorker {
+            log_id,
+            config: &config,
+            sync_state: &sync_state,
+            progress: &progress,
+            subscriptions: &subscriptions,
+            sync_started_at,
         };
 
-        tracing::info!("Starting OpenFDA full sync with alphabetical partitioning (log_id: {})", log_id);
+        // A database error in any worker aborts the run (remaining workers are dropped)
+        futures::stream::iter(pending)
+            .map(|index| self.sync_partition(worker, index))
+            .buffer_unordered(workers)
+            .try_collect::<Vec<()>>()
+            .await?;
+
+        // Workers stop at their next batch on shutdown: persist where they stopped
+        if Shutdown::requested() {
+            self.repo.save_resume_state(log_id, &progress.lock().await.plan).await?;
+            return Err(Shutdown::interrupted());
+        }
 
-        // First, get total count
-        let initial_response = self.fetch_batch_with_search(&config, None, 0, 1).await?;
-        let total_expected = initial_response.meta.results.total as i32;
-        self.repo.set_total_expected(log_id, total_expected).await?;
-        tracing::info!("OpenFDA API reports {} total records", total_expected);
+        // Workers stop at their next batch once cancellation is requested
+        if sync_state.read().await.cancel_requested {
+            tracing::info!("OpenFDA sync cancelled by user");
+            self.repo.fail_sync_log(log_id, "Cancelled by user").await?;
+            return Ok(());
+        }
 
-        // Track which NDCs we've seen to avoid duplicates
-        let mut seen_ndcs = std::collections::HashSet::new();
+        let plan = progress.into_inner().plan;
+        let totals = plan.totals;
 
-        // Process each partition
-        for search_filter in &search_prefixes {
-            // Check for cancellation
-            {
-                let state = sync_state.read().await;
-                if state.cancel_requested {
-                    tracing::info!("OpenFDA sync cancelled by user");
-                    self.repo.fail_sync_log(log_id, "Cancelled by user").await?;
-                    return Ok(());
-                }
+        // Complete sync
+        let processing_time_ms = start_time.elapsed().as_millis() as i32;
+        self.repo.complete_sync_log_full(
+            log_id,
+            totals.fetched,
+            totals.inserted,
+            totals.updated,
+            totals.skipped,
+            totals.failed,
+            processing_time_ms,
+        ).await?;
+        QueryCache::invalidate_source(CatalogSource::OpenFda);
+        metrics::record_sync_run(
+            "openfda",
+            totals.fetched,
+            totals.inserted,
+            totals.updated,
+            totals.failed,
+            start_time.elapsed(),
+        );
+
+        // Only full runs reset the age that forces the next full sync
+        let today = chrono::Utc::now().date_naive();
+        let full_synced_on = if incremental {
+            self.repo.get_latest_checkpoint().await?.and_then(|checkpoint| checkpoint.full_synced_on)
+        } else {
+            Some(today)
+        };
+        self.repo.save_checkpoint(log_id, &DeltaCheckpoint {
+            dataset_last_updated: plan.dataset_last_updated,
+            partition_totals: plan.partition_totals,
+            synced_on: today,
+            full_synced_on,
+        }).await?;
+
+        // Guardrails: suspect runs don't count as a successful refresh
+        let guard = SyncAnomalyService::new(self.repo.pool().clone());
+        let anomalies = guard.assess(&SyncRunStats {
+            catalog_size,
+            fetched: totals.fetched as i64,
+            inserted: totals.inserted as i64,
+            updated: totals.updated as i64,
+            failed: totals.failed as i64,
+            removed: 0,
+            altered: guard.count_catalog_changes(log_id).await?,
+            incremental,
+        });
+        if anomalies.is_empty()
+            || self.repo.mark_sync_suspect(log_id, &serde_json::to_value(&anomalies)?).await?
+        {
+            guard.report("openfda", log_id, &anomalies).await;
+        }
+
+        // Clear sync state
+        self.clear_sync_state(&sync_state).await;
+
+        if let Err(e) = subscriptions.dispatch_pending_events().await {
+            tracing::error!("Catalog subscription dispatch failed: {:?}", e);
+        }
+
+        tracing::info!(
+            "OpenFDA sync completed: {} records fetched, {} inserted, {} updated, {} skipped, {} failed in {}ms",
+            totals.fetched, totals.inserted, totals.updated, totals.skipped, totals.failed, processing_time_ms
+        );
+
+        Ok(())
+    }
+
+    /// Fetch one partition page by page. Returns early (leaving the partition unfinished)
+    /// on cancellation, on shutdown or when the sync limit is reached.
+    async fn sync_partition(&self, worker: &PartitionWorker<'_>, index: usize) -> Result<()> {
+        let config = worker.config;
+        let batch_size = config.batch_size;
+        let (search_filter, mut skip) = {
+            let progress = worker.progress.lock().await;
+            let partition = &progress.plan.partitions[index];
+            (partition.filter.clone(), partition.skip)
+        };
+
+        let partition_name = search_filter.as_deref().unwrap_or("all");
+        tracing::info!("Processing partition: {}", partition_name);
+
+        let max_skip = 25000; // OpenFDA limit
+
+        loop {
+            // Check for cancellation and shutdown
+            if worker.sync_state.read().await.cancel_requested || Shutdown::requested() {
+                return Ok(());
             }
 
             // Check sync limit
             if let Some(limit) = config.sync_limit {
-                if total_fetched >= limit as i32 {
+                if worker.progress.lock().await.plan.totals.fetched >= limit as i32 {
                     tracing::info!("Reached sync limit of {} records", limit);
-                    break;
+                    return Ok(());
                 }
             }
 
-            let partition_name = search_filter.as_deref().unwrap_or("all");
-            tracing::info!("Processing partition: {}", partition_name);
+            if skip >= max_skip {
+                tracing::warn!("Reached max skip {} for partition {}, moving to next", max_skip, partition_name);
+                break;
+            }
 
-            let mut skip = 0;
-            let max_skip = 25000; // OpenFDA limit
+            let batch_number = {
+                let mut progress = worker.progress.lock().await;
+                progress.plan.totals.batches += 1;
+                progress.plan.totals.batches
+            };
+            tracing::info!(
+                "Fetching batch {} from OpenFDA API: partition={}, skip={}, limit={}",
+                batch_number, partition_name, skip, batch_size
+            );
 
-            loop {
-                if skip >= max_skip {
-                    tracing::warn!("Reached max skip {} for partition {}, moving to next", max_skip, partition_name);
+            // Fetch from API with retries
+            let api_start = Instant::now();
+            let api_response = match self.fetch_batch_with_search(config, Some(worker.log_id), search_filter.as_deref(), skip, batch_size).await {
+                Ok(response) => response,
+                Err(e) => {
+                    // Log error but continue with next partition
+                    tracing::error!("Failed to fetch batch {} (partition {}): {:?}", batch_number, partition_name, e);
                     break;
                 }
+            };
+            let api_time_ms = api_start.elapsed().as_millis() as i32;
+            let batch_count = api_response.results.len();
 
-                current_batch += 1;
-                tracing::info!(
-                    "Fetching batch {} from OpenFDA API: partition={}, skip={}, limit={}",
-                    current_batch, partition_name, skip, batch_size
-                );
+            // Convert records, skipping NDCs another partition already handled
+            let mut batch_skipped = 0;
+            let mut entries = Vec::with_capacity(batch_count);
+            {
+                let mut progress = worker.progress.lock().await;
 
-                // Fetch from API with retries
-                let api_start = Instant::now();
-                let api_response = match self.fetch_batch_with_search(&config, search_filter.as_deref(), skip, batch_size).await {
-                    Ok(response) => response,
-                    Err(e) => {
-                        // Log error but continue with next partition
-                        tracing::error!("Failed to fetch batch {} (partition {}): {:?}", current_batch, partition_name, e);
-                        break;
+                if skip == 0 {
+                    if let Some(filter) = search_filter.as_deref().filter(|f| f.starts_with("brand_name:")) {
+                        progress.plan.partition_totals.insert(filter.to_string(), api_response.meta.results.total);
                     }
-                };
-                let api_time_ms = api_start.elapsed().as_millis() as i32;
-                total_api_time_ms += api_time_ms;
+                }
 
-                let batch_count = api_response.results.len();
                 if batch_count == 0 {
                     tracing::info!("No more records in partition {}", partition_name);
                     break;
                 }
 
-                // Convert and insert records (skip duplicates)
-                let mut batch_inserted = 0;
-                let mut batch_updated = 0;
-                let mut batch_skipped = 0;
-                let mut batch_failed = 0;
-                let mut entries = Vec::with_capacity(batch_count);
+                // Bloom filter hits from before a resume may be false positives: only NDCs
+                // this run actually upserted count as duplicates
+                let maybe_seen: Vec<String> = api_response
+                    .results
+                    .iter()
+                    .filter(|r| !progress.handled_ndcs.contains(&r.product_ndc))
+                    .filter(|r| progress.plan.seen_ndcs.contains(&r.product_ndc))
+                    .map(|r| r.product_ndc.clone())
+                    .collect();
+                if !maybe_seen.is_empty() {
+                    let synced = self.repo.filter_synced_since(&maybe_seen, worker.sync_started_at).await?;
+                    progress.handled_ndcs.extend(synced);
+                }
 
                 for drug_record in api_response.results {
                     // Skip if we've already processed this NDC
-                    if seen_ndcs.contains(&drug_record.product_ndc) {
+                    if !progress.handled_ndcs.insert(drug_record.product_ndc.clone()) {
                         batch_skipped += 1;
                         continue;
                     }
-                    seen_ndcs.insert(drug_record.product_ndc.clone());
+                    progress.plan.seen_ndcs.insert(&drug_record.product_ndc);
 
                     match drug_record.to_catalog_entry() {
                         Ok(entry) => entries.push(entry),
@@ -283,101 +739,105 @@ impl OpenFdaService {
                                 drug_record.product_ndc, e
                             );
                             batch_skipped += 1;
-                            continue;
                         }
                     }
                 }
+            }
 
-                // Batch upsert
-                if !entries.is_empty() {
-                    match self.repo.batch_upsert(entries).await {
-                        Ok((inserted, updated)) => {
-                            batch_inserted = inserted;
-                            batch_updated = updated;
-                        }
-                        Err(e) => {
-                            tracing::error!("Batch upsert failed: {:?}", e);
-                            batch_failed = batch_count as i32 - batch_skipped;
-                        }
+            // Batch upsert
+            let mut batch_inserted = 0;
+            let mut batch_updated = 0;
+            let mut batch_failed = 0;
+            if !entries.is_empty() {
+                // Diff subscribed records before they are overwritten
+                if let Err(e) = worker.subscriptions.capture_openfda_changes(&entries, Some(worker.log_id)).await {
+                    tracing::warn!("Catalog change capture failed: {:?}", e);
+                }
+
+                match self.repo.batch_upsert(entries).await {
+                    Ok((inserted, updated)) => {
+                        batch_inserted = inserted;
+                        batch_updated = updated;
+                    }
+                    Err(e) => {
+                        tracing::error!("Batch upsert failed: {:?}", e);
+                        batch_failed = batch_count as i32 - batch_skipped;
                     }
                 }
+            }
+
+            skip += batch_size;
 
-                total_fetched += batch_count as i32;
-                total_inserted += batch_inserted;
-                total_updated += batch_updated;
-                total_skipped += batch_skipped;
-                total_failed += batch_failed;
+            // Update progress (under the lock so concurrent workers never write stale totals)
+            {
+                let mut progress = worker.progress.lock().await;
+                let totals = &mut progress.plan.totals;
+                totals.fetched += batch_count as i32;
+                totals.inserted += batch_inserted;
+                totals.updated += batch_updated;
+                totals.skipped += batch_skipped;
+                totals.failed += batch_failed;
+                totals.api_time_ms += api_time_ms;
+                let totals = *totals;
 
-                // Update progress
                 self.repo.update_sync_progress(
-                    log_id,
-                    total_fetched,
-                    total_inserted,
-                    total_updated,
-                    total_skipped,
-                    total_failed,
-                    current_batch,
+                    worker.log_id,
+                    totals.fetched,
+                    totals.inserted,
+                    totals.updated,
+                    totals.skipped,
+                    totals.failed,
+                    totals.batches,
                     api_time_ms,
                 ).await?;
 
-                tracing::info!(
-                    "Batch {} complete: fetched={}, inserted={}, updated={}, skipped={}, failed={}",
-                    current_batch, batch_count, batch_inserted, batch_updated, batch_skipped, batch_failed
-                );
-
-                skip += batch_size;
-
-                // Rate limiting delay
-                tokio::time::sleep(Duration::from_millis(config.batch_delay_ms)).await;
-
-                // For the "all" partition (no filter), we only get first 25000
-                // Then we rely on letter partitions for the rest
-                if search_filter.is_none() && skip >= max_skip {
-                    break;
+                progress.plan.partitions[index].skip = skip;
+                progress.batches_since_checkpoint += 1;
+                if progress.batches_since_checkpoint >= RESUME_CHECKPOINT_BATCHES {
+                    progress.batches_since_checkpoint = 0;
+                    self.repo.save_resume_state(worker.log_id, &progress.plan).await?;
                 }
             }
-        }
 
-        // Complete sync
-        let processing_time_ms = start_time.elapsed().as_millis() as i32;
-        self.repo.complete_sync_log_full(
-            log_id,
-            total_fetched,
-            total_inserted,
-            total_updated,
-            total_skipped,
-            total_failed,
-            processing_time_ms,
-        ).await?;
+            metrics::record_sync_batch("openfda", api_start.elapsed());
+            tracing::info!(
+                "Batch {} complete: fetched={}, inserted={}, updated={}, skipped={}, failed={}",
+                batch_number, batch_count, batch_inserted, batch_updated, batch_skipped, batch_failed
+            );
 
-        // Clear sync state
-        {
-            let mut state = sync_state.write().await;
-            state.active_sync_id = None;
-            state.cancel_requested = false;
+            // For the "all" partition (no filter), we only get first 25000
+            // Then we rely on letter partitions for the rest
+            if search_filter.is_none() && skip >= max_skip {
+                break;
+            }
         }
 
-        tracing::info!(
-            "OpenFDA sync completed: {} records fetched, {} inserted, {} updated, {} skipped, {} failed in {}ms",
-            total_fetched, total_inserted, total_updated, total_skipped, total_failed, processing_time_ms
-        );
+        // Partition done: a resumed run skips it
+        let mut progress = worker.progress.lock().await;
+        progress.plan.partitions[index].done = true;
+        self.repo.save_resume_state(worker.log_id, &progress.plan).await?;
 
         Ok(())
     }
 
-    /// Fetch a batch with optional search filter
+    /// Fetch a batch with optional search filter. The heartbeat of the sync `log_id`
+    /// (if any) is touched before every wait, so a long backoff doesn't look stale.
     async fn fetch_batch_with_search(
         &self,
         config: &OpenFdaSyncConfig,
+        log_id: Option<Uuid>,
         search: Option<&str>,
         skip: usize,
         limit: usize,
     ) -> Result<OpenFdaApiResponse> {
-        let url = if let Some(search_query) = search {
+        let mut url = if let Some(search_query) = search {
             format!("{}?search={}&limit={}&skip={}", config.api_base_url, search_query, limit, skip)
         } else {
             format!("{}?limit={}&skip={}", config.api_base_url, limit, skip)
         };
+        if let Some(api_key) = &config.api_key {
+            url.push_str(&format!("&api_key={}", api_key));
+        }
 
         let mut last_error = None;
 
@@ -385,16 +845,24 @@ impl OpenFdaService {
             if attempt > 0 {
                 let delay = Duration::from_secs(1 << attempt);
                 tracing::warn!("Retry attempt {} after {:?} delay", attempt + 1, delay);
+                self.touch_heartbeat(log_id).await;
                 tokio::time::sleep(delay).await;
             }
 
+            // Concurrent partition workers draw from the same budget
+            self.request_budget.acquire().await;
+
             match self.http_client.get(&url).send().await {
                 Ok(response) => {
                     if !response.status().is_success() {
                         let status = response.status();
+                        if status.as_u16() != 404 {
+                            metrics::record_sync_api_error("openfda", status.as_str());
+                        }
                         if status.as_u16() == 429 {
-                            tracing::warn!("Rate limited by OpenFDA API, backing off...");
-                            tokio::time::sleep(Duration::from_secs(60)).await;
+                            tracing::warn!("Rate limited by OpenFDA API, pausing all workers...");
+                            self.touch_heartbeat(log_id).await;
+                            self.request_budget.pause(Duration::from_secs(60));
                             continue;
                         }
                         if status.as_u16() == 404 {
@@ -423,16 +891,18 @@ impl OpenFdaService {
                     match response.json::<OpenFdaApiResponse>().await {
                         Ok(data) => return Ok(data),
                         Err(e) => {
+                            metrics::record_sync_api_error("openfda", "parse");
                             last_error = Some(AppError::Internal(anyhow::anyhow!(
-                                "Failed to parse OpenFDA response: {}", e
+                                "Failed to parse OpenFDA response: {}", e.without_url()
                             )));
                             continue;
                         }
                     }
                 }
                 Err(e) => {
+                    metrics::record_sync_api_error("openfda", "network");
                     last_error = Some(AppError::Internal(anyhow::anyhow!(
-                        "HTTP request failed: {}", e
+                        "HTTP request failed: {}", e.without_url() // The URL may carry the API key
                     )));
                     continue;
                 }
@@ -444,6 +914,14 @@ impl OpenFdaService {
         }))
     }
 
+    async fn touch_heartbeat(&self, log_id: Option<Uuid>) {
+        if let Some(log_id) = log_id {
+            if let Err(e) = self.repo.touch_heartbeat(log_id).await {
+                tracing::warn!("Failed to record heartbeat of OpenFDA sync {}: {:?}", log_id, e);
+            }
+        }
+    }
+
     /// Fetch a batch with retry logic (legacy - kept for compatibility)
     async fn fetch_batch_with_retry(
         &self,
@@ -519,13 +997,74 @@ impl OpenFdaService {
         self.repo.cancel_sync(sync_id, user_id).await
     }
 
+    /// Dry run: fetch up to `limit` records and report what a sync would change, without writing
+    pub async fn preview_sync(&self, limit: Option<usize>) -> Result<SyncPreview> {
+        let limit = limit.unwrap_or(DEFAULT_PREVIEW_LIMIT).clamp(1, MAX_PREVIEW_LIMIT);
+        let mut preview = SyncPreview::new("openfda");
+        let mut entries = Vec::new();
+
+        while (preview.records_fetched as usize) < limit {
+            let batch_limit = self.config.batch_size.min(limit - preview.records_fetched as usize);
+            let api_response = self
+                .fetch_batch_with_search(&self.config, None, None, preview.records_fetched as usize, batch_limit)
+                .await?;
+            if api_response.results.is_empty() {
+                break;
+            }
+
+            preview.records_fetched += api_response.results.len() as i64;
+            for drug_record in api_response.results {
+                match drug_record.to_catalog_entry() {
+                    Ok(entry) => entries.push(entry),
+                    Err(e) => {
+                        tracing::debug!("Dry run: failed to convert drug record {}: {}", drug_record.product_ndc, e);
+                        preview.invalid += 1;
+                    }
+                }
+            }
+        }
+
+        let ndcs: Vec<String> = entries.iter().map(|e| e.product_ndc.clone()).collect();
+        let existing: HashMap<String, serde_json::Value> = self
+            .repo
+            .find_by_ndcs(&ndcs)
+            .await?
+            .into_iter()
+            .map(|e| (e.product_ndc.clone(), serde_json::to_value(&e).unwrap_or_default()))
+            .collect();
+
+        for entry in &entries {
+            let incoming = serde_json::to_value(entry).unwrap_or_default();
+            preview.record(
+                &entry.product_ndc,
+                &entry.brand_name,
+                existing.get(&entry.product_ndc),
+                &incoming,
+                PREVIEW_FIELDS,
+            );
+        }
+
+        tracing::info!(
+            "OpenFDA dry run: fetched={}, would_insert={}, would_update={}, unchanged={}, invalid={}",
+            preview.records_fetched, preview.would_insert, preview.would_update, preview.unchanged, preview.invalid
+        );
+
+        Ok(preview)
+    }
+
     /// Sync data from OpenFDA API (legacy synchronous method)
     pub async fn sync_from_api(&self, limit: Option<usize>) -> Result<OpenFdaSyncLog> {
         let log_id = self.repo.start_sync_log().await?;
 
-        match self.perform_sync(limit).await {
+        match self.perform_sync(limit, log_id).await {
             Ok((fetched, inserted, updated)) => {
                 self.repo.complete_sync_log(log_id, fetched, inserted, updated).await?;
+                QueryCache::invalidate_source(CatalogSource::OpenFda);
+
+                let subscriptions = CatalogSubscriptionService::new(self.repo.pool().clone());
+                if let Err(e) = subscriptions.dispatch_pending_events().await {
+                    tracing::error!("Catalog subscription dispatch failed: {:?}", e);
+                }
 
                 Ok(OpenFdaSyncLog {
                     id: log_id,
@@ -548,6 +1087,9 @@ impl OpenFdaService {
                     sync_type: Some("manual".to_string()),
                     cancelled_at: None,
                     cancelled_by: None,
+                    last_heartbeat_at: None,
+                    resume_count: 0,
+                    anomalies: None,
                 })
             }
             Err(e) => {
@@ -558,13 +1100,14 @@ impl OpenFdaService {
         }
     }
 
-    async fn perform_sync(&self, limit: Option<usize>) -> Result<(i32, i32, i32)> {
+    async fn perform_sync(&self, limit: Option<usize>, log_id: Uuid) -> Result<(i32, i32, i32)> {
         let batch_size = 100; // OpenFDA API limit
         let total_limit = limit.unwrap_or(150000); // Default: fetch 150000 records for enterprise-grade catalog
         let mut skip = 0;
         let mut total_fetched: i32 = 0;
         let mut total_inserted = 0;
         let mut total_updated = 0;
+        let subscriptions = CatalogSubscriptionService::new(self.repo.pool().clone());
 
         loop {
             if total_fetched >= total_limit as i32 {
@@ -614,6 +1157,9 @@ impl OpenFdaService {
             }
 
             // Batch upsert
+            if let Err(e) = subscriptions.capture_openfda_changes(&entries, Some(log_id)).await {
+                tracing::warn!("Catalog change capture failed: {:?}", e);
+            }
             let (inserted, updated) = self.repo.batch_upsert(entries).await?;
             total_inserted += inserted;
             total_updated += updated;
@@ -634,16 +1180,48 @@ impl OpenFdaService {
     }
 
     /// Search catalog
-    pub async fn search(&self, request: OpenFdaSearchRequest) -> Result<Vec<OpenFdaCatalogResponse>> {
-        let entries = self.repo.search(&request).await?;
-        let responses = entries.into_iter().map(Into::into).collect();
-        Ok(responses)
+    pub async fn search(
+        &self,
+        request: OpenFdaSearchRequest,
+        engine: Option<&Arc<dyn SearchEngine>>,
+    ) -> Result<FacetedSearchResponse<OpenFdaCatalogResponse>> {
+        let query = request.engine_query()?;
+
+        match (search_engine::search_with_fallback(engine, SearchIndex::OpenFda, query.as_ref()).await, engine) {
+            (Some(hits), Some(engine)) => {
+                let entries = self.repo.find_by_ndcs(&hits.keys).await?;
+                let entries = search_engine::in_hit_order(entries, &hits.keys, |e| e.product_ndc.clone());
+                Ok(FacetedSearchResponse {
+                    results: entries.into_iter().map(Into::into).collect(),
+                    facets: hits.facets,
+                    facets_as_of: None,
+                    total: Some(hits.total),
+                    engine: engine.name().to_string(),
+                })
+            }
+            _ => {
+                let entries = self.repo.search(&request).await?;
+                Ok(FacetedSearchResponse::from_postgres(entries.into_iter().map(Into::into).collect()))
+            }
+        }
     }
 
-    /// Get by NDC
+    /// Get by NDC (cached until the next catalog sync)
     pub async fn get_by_ndc(&self, ndc: &str) -> Result<Option<OpenFdaCatalogResponse>> {
-        let entry = self.repo.find_by_ndc(ndc).await?;
-        Ok(entry.map(Into::into))
+        QueryCache::get_or_load(QueryCacheKind::OpenFdaNdc, ndc.trim(), || async {
+            let entry = self.repo.find_by_ndc(ndc).await?;
+            Ok(entry.map(Into::into))
+        }).await
+    }
+
+    /// Version history of an NDC entry, newest first
+    pub async fn get_history(&self, ndc: &str) -> Result<CatalogHistoryResponse> {
+        let versions = self.repo.get_history(ndc).await?;
+        if versions.is_empty() {
+            return Err(AppError::NotFound(format!("No catalog history for NDC {}", ndc)));
+        }
+
+        Ok(CatalogHistoryResponse::from_versions(ndc, versions))
     }
 
     /// Get catalog statistics
@@ -681,7 +1259,7 @@ impl OpenFdaService {
     }
 }
 
-#[derive(Debug, serde::Serialize)]
+#[derive(Debug, serde::Serialize, ToSchema)]
 pub struct CatalogStats {
     pub total_entries: i64,
     pub last_sync_at: Option<chrono::DateTime<chrono::Utc>>,
@@ -724,17 +1302,28 @@ impl OpenFdaSyncScheduler {
         );
 
         loop {
-            ticker.tick().await;
+            let deadline = ticker.tick().await;
+            metrics::record_scheduler_lag("openfda", deadline);
+            if crate::services::MaintenanceService::pauses("openfda")
+                || !crate::services::SchedulerLeader::leads("openfda").await
+            {
+                continue;
+            }
             self.run_scheduled_sync().await;
         }
     }
 
     /// Run a single scheduled sync
     pub async fn run_scheduled_sync(&self) {
-        tracing::info!("Running scheduled OpenFDA sync...");
-
         let service = OpenFdaService::from_pool(self.pool.clone());
 
+        if SyncSourceService::new(self.pool.clone()).is_scheduler_paused("openfda").await {
+            tracing::info!("OpenFDA sync scheduler is paused by an admin, skipping scheduled sync");
+            return;
+        }
+
+        tracing::info!("Running scheduled OpenFDA sync...");
+
         // Check if sync is needed
         match service.needs_refresh().await {
             Ok(needs_refresh) => {
@@ -762,8 +1351,22 @@ impl OpenFdaSyncScheduler {
             }
         }
 
+        // Finish an interrupted run before starting a new one
+        match service.repo.find_resumable_sync().await {
+            Ok(Some(sync_id)) => {
+                match service.resume_sync(sync_id, self.pool.clone()).await {
+                    Ok(_) => tracing::info!("Scheduled run resumed interrupted OpenFDA sync {}", sync_id),
+                    Err(e) => tracing::error!("Failed to resume OpenFDA sync {}: {:?}", sync_id, e),
+                }
+                return;
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Failed to look up resumable OpenFDA syncs: {:?}", e),
+        }
+
         // Start background sync
-        match service.start_background_sync("scheduled", self.pool.clone()).await {
+        // Scheduled runs are incremental; delta falls back to full when the checkpoint is stale
+        match service.start_background_sync("delta", self.pool.clone()).await {
             Ok(sync_id) => {
                 tracing::info!("Scheduled OpenFDA sync started with ID: {}", sync_id);
             }
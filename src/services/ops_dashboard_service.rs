@@ -0,0 +1,200 @@
+/// Admin Operations Dashboard Service
+///
+/// Aggregates what on-call engineers used to assemble from several endpoints:
+/// - background job queue depth and recent failures per job type
+/// - last runs of the alert scheduler checks, which instance leads each scheduler, and
+///   this instance's ERP sync scheduler
+/// - saturation of this instance's IP rate limiters
+/// - webhook delivery failure rate over the last 24 hours
+/// - Claude API spend of the current month (`ai_api_usage` records every successful call)
+/// - database pool utilization of this instance
+
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use crate::{
+    middleware::{error_handling::Result, ip_rate_limiter::RateLimiter},
+    models::ops_dashboard::*,
+    services::{erp::ErpSyncScheduler, SchedulerLeader},
+};
+
+/// Window of the webhook delivery failure rate
+const WEBHOOK_WINDOW_HOURS: i64 = 24;
+
+pub struct OpsDashboardService {
+    db_pool: PgPool,
+}
+
+impl OpsDashboardService {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self { db_pool }
+    }
+
+    pub async fn overview(&self) -> Result<OpsDashboard> {
+        let (background_jobs, schedulers, webhooks, ai_spend) = tokio::try_join!(
+            self.background_jobs(),
+            self.schedulers(),
+            self.webhooks(),
+            self.ai_spend(),
+        )?;
+
+        Ok(OpsDashboard {
+            background_jobs,
+            schedulers,
+            rate_limiters: RateLimiter::snapshots(),
+            webhooks,
+            ai_spend,
+            database: self.database(),
+            generated_at: Utc::now(),
+        })
+    }
+
+    pub async fn background_jobs(&self) -> Result<JobQueueSummary> {
+        let job_types = sqlx::query_as::<_, JobTypeActivity>(
+            r#"
+            SELECT
+                t.job_type,
+                t.is_enabled,
+                t.max_concurrency,
+                COUNT(j.id) FILTER (WHERE j.status = 'queued') AS queued,
+                COUNT(j.id) FILTER (WHERE j.status = 'queued' AND j.run_at <= NOW()) AS due,
+                COUNT(j.id) FILTER (WHERE j.status = 'running') AS running,
+                COUNT(j.id) FILTER (
+                    WHERE j.status = 'succeeded' AND j.completed_at > NOW() - INTERVAL '24 hours'
+                ) AS succeeded_last_24h,
+                COUNT(j.id) FILTER (
+                    WHERE j.status = 'failed' AND j.completed_at > NOW() - INTERVAL '24 hours'
+                ) AS failed_last_24h,
+                MIN(j.run_at) FILTER (WHERE j.status = 'queued' AND j.run_at <= NOW()) AS oldest_due_at
+            FROM background_job_types t
+            LEFT JOIN background_jobs j
+                ON j.job_type = t.job_type
+               AND (j.status IN ('queued', 'running') OR j.completed_at > NOW() - INTERVAL '24 hours')
+            GROUP BY t.job_type, t.is_enabled, t.max_concurrency
+            ORDER BY t.job_type
+            "#,
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(JobQueueSummary::new(job_types))
+    }
+
+    pub async fn schedulers(&self) -> Result<SchedulerSummary> {
+        let alert_checks = sqlx::query_as::<_, ScheduledCheckStatus>(
+            r#"
+            SELECT
+                job_type,
+                is_enabled,
+                interval_seconds,
+                last_started_at,
+                last_completed_at,
+                last_status,
+                last_error,
+                next_run_at,
+                is_enabled AND next_run_at + make_interval(secs => interval_seconds) < NOW() AS overdue,
+                COALESCE(lease_expires_at > NOW(), FALSE) AS running
+            FROM alert_scheduler_jobs
+            ORDER BY job_type
+            "#,
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let leaders = sqlx::query_as::<_, SchedulerLeaderStatus>(
+            r#"
+            SELECT
+                scheduler,
+                instance_id,
+                acquired_at,
+                heartbeat_at,
+                heartbeat_at < NOW() - make_interval(secs => $1) AS stale
+            FROM scheduler_leaders
+            ORDER BY scheduler
+            "#,
+        )
+        .bind(SchedulerLeader::stale_seconds() as f64)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let erp_sync = ErpSyncScheduler::heartbeat().map(|heartbeat| ErpSchedulerStatus {
+            last_tick_at: heartbeat.last_tick_at,
+            poll_minutes: heartbeat.poll_minutes,
+        });
+
+        Ok(SchedulerSummary { alert_checks, leaders, erp_sync })
+    }
+
+    pub async fn webhooks(&self) -> Result<WebhookDeliverySummary> {
+        let since = Utc::now() - Duration::hours(WEBHOOK_WINDOW_HOURS);
+
+        let (succeeded, failed, pending) = sqlx::query_as::<_, (i64, i64, i64)>(
+            r#"
+            SELECT
+                COUNT(*) FILTER (WHERE status = 'succeeded'),
+                COUNT(*) FILTER (WHERE status = 'failed'),
+                COUNT(*) FILTER (WHERE status = 'pending')
+            FROM webhook_deliveries
+            WHERE created_at > $1
+            "#,
+        )
+        .bind(since)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        let (failing_endpoints, disabled_endpoints) = sqlx::query_as::<_, (i64, i64)>(
+            r#"
+            SELECT
+                COUNT(*) FILTER (WHERE is_active AND consecutive_failures > 0),
+                COUNT(*) FILTER (WHERE NOT is_active AND disabled_reason IS NOT NULL)
+            FROM webhook_endpoints
+            "#,
+        )
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        Ok(WebhookDeliverySummary {
+            window_hours: WEBHOOK_WINDOW_HOURS,
+            succeeded,
+            failed,
+            pending,
+            failure_rate_percent: share_percent(failed, succeeded + failed),
+            failing_endpoints,
+            disabled_endpoints,
+        })
+    }
+
+    pub async fn ai_spend(&self) -> Result<AiSpendSummary> {
+        let now = Utc::now();
+        let start = month_start(now);
+
+        let (calls, total_tokens, spend_usd) = sqlx::query_as::<_, (i64, i64, f64)>(
+            r#"
+            SELECT
+                COUNT(*),
+                COALESCE(SUM(total_tokens), 0)::BIGINT,
+                COALESCE(SUM(total_cost_usd), 0)::DOUBLE PRECISION
+            FROM ai_api_usage
+            WHERE created_at >= $1
+            "#,
+        )
+        .bind(start)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        Ok(AiSpendSummary {
+            month_start: start,
+            calls,
+            total_tokens,
+            spend_usd: (spend_usd * 100.0).round() / 100.0,
+            projected_month_usd: project_month_spend(spend_usd, now),
+        })
+    }
+
+    pub fn database(&self) -> DbPoolSummary {
+        DbPoolSummary::new(
+            self.db_pool.options().get_max_connections(),
+            self.db_pool.size(),
+            self.db_pool.num_idle(),
+        )
+    }
+}
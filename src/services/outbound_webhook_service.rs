@@ -0,0 +1,622 @@
+/// Outbound Webhook Service
+///
+/// Endpoint management and delivery for platform event webhooks. `emit` queues one
+/// delivery per active endpoint subscribed to the event; the delivery worker (started in
+/// main) POSTs due deliveries, signed like catalog subscription webhooks
+/// (`X-Webhook-Signature: sha256=<hex>`), and retries failures with backoff.
+
+use chrono::Utc;
+use sqlx::PgPool;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+use crate::{
+    middleware::error_handling::{AppError, Result},
+    models::webhook::*,
+    services::{catalog_subscription_service::sign_payload, EncryptionService},
+};
+
+/// Endpoints a user may register
+const MAX_ENDPOINTS_PER_USER: i64 = 20;
+
+pub struct OutboundWebhookService {
+    db_pool: PgPool,
+    http_client: reqwest::Client,
+}
+
+impl OutboundWebhookService {
+    pub fn new(db_pool: PgPool) -> Self {
+        let http_client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .unwrap_or_default();
+
+        Self { db_pool, http_client }
+    }
+
+    // ========================================================================
+    // ENDPOINTS
+    // ========================================================================
+
+    pub async fn create_endpoint(
+        &self,
+        user_id: Uuid,
+        request: CreateWebhookEndpointRequest,
+    ) -> Result<WebhookEndpointWithSecret> {
+        let url = validate_endpoint_url(&request.url)?;
+        let event_types = event_type_names(&request.event_types)?;
+        let description = normalize_description(request.description)?;
+
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM webhook_endpoints WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_one(&self.db_pool)
+            .await?;
+        if count >= MAX_ENDPOINTS_PER_USER {
+            return Err(AppError::BadRequest(format!(
+                "At most {} webhook endpoints can be registered",
+                MAX_ENDPOINTS_PER_USER
+            )));
+        }
+
+        let secret = generate_secret();
+        let endpoint = sqlx::query_as::<_, WebhookEndpoint>(
+            r#"
+            INSERT INTO webhook_endpoints (user_id, url, description, secret_encrypted, event_types)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING *
+            "#
+        )
+        .bind(user_id)
+        .bind(&url)
+        .bind(&description)
+        .bind(encryption_service()?.encrypt(&secret)?)
+        .bind(&event_types)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        tracing::info!("Webhook endpoint {} registered by user {} for {:?}", endpoint.id, user_id, event_types);
+
+        Ok(WebhookEndpointWithSecret { endpoint, secret })
+    }
+
+    pub async fn list_endpoints(&self, user_id: Uuid) -> Result<Vec<WebhookEndpoint>> {
+        let endpoints = sqlx::query_as::<_, WebhookEndpoint>(
+            "SELECT * FROM webhook_endpoints WHERE user_id = $1 ORDER BY created_at DESC"
+        )
+        .bind(user_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(endpoints)
+    }
+
+    pub async fn get_endpoint(&self, endpoint_id: Uuid, user_id: Uuid) -> Result<WebhookEndpoint> {
+        sqlx::query_as::<_, WebhookEndpoint>(
+            "SELECT * FROM webhook_endpoints WHERE id = $1 AND user_id = $2"
+        )
+        .bind(endpoint_id)
+        .bind(user_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Webhook endpoint not found".to_string()))
+    }
+
+    pub async fn update_endpoint(
+        &self,
+        endpoint_id: Uuid,
+        user_id: Uuid,
+        request: UpdateWebhookEndpointRequest,
+    ) -> Result<WebhookEndpoint> {
+        self.get_endpoint(endpoint_id, user_id).await?;
+
+        let url = request.url.as_deref().map(validate_endpoint_url).transpose()?;
+        let event_types = request.event_types.as_deref().map(event_type_names).transpose()?;
+        let description = normalize_description(request.description)?;
+
+        let endpoint = sqlx::query_as::<_, WebhookEndpoint>(
+            r#"
+            UPDATE webhook_endpoints SET
+                url = COALESCE($3, url),
+                description = COALESCE($4, description),
+                event_types = COALESCE($5, event_types),
+                is_active = COALESCE($6, is_active),
+                consecutive_failures = CASE WHEN $6 THEN 0 ELSE consecutive_failures END,
+                disabled_reason = CASE WHEN $6 THEN NULL ELSE disabled_reason END,
+                updated_at = NOW()
+            WHERE id = $1 AND user_id = $2
+            RETURNING *
+            "#
+        )
+        .bind(endpoint_id)
+        .bind(user_id)
+        .bind(url)
+        .bind(description)
+        .bind(event_types)
+        .bind(request.is_active)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        Ok(endpoint)
+    }
+
+    /// Replace the signing secret; deliveries sent from now on use the new one
+    pub async fn rotate_secret(&self, endpoint_id: Uuid, user_id: Uuid) -> Result<WebhookEndpointWithSecret> {
+        let secret = generate_secret();
+        let endpoint = sqlx::query_as::<_, WebhookEndpoint>(
+            r#"
+            UPDATE webhook_endpoints SET secret_encrypted = $3, updated_at = NOW()
+            WHERE id = $1 AND user_id = $2
+            RETURNING *
+            "#
+        )
+        .bind(endpoint_id)
+        .bind(user_id)
+        .bind(encryption_service()?.encrypt(&secret)?)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Webhook endpoint not found".to_string()))?;
+
+        tracing::info!("Webhook endpoint {} secret rotated by user {}", endpoint_id, user_id);
+
+        Ok(WebhookEndpointWithSecret { endpoint, secret })
+    }
+
+    pub async fn delete_endpoint(&self, endpoint_id: Uuid, user_id: Uuid) -> Result<()> {
+        let result = sqlx::query("DELETE FROM webhook_endpoints WHERE id = $1 AND user_id = $2")
+            .bind(endpoint_id)
+            .bind(user_id)
+            .execute(&self.db_pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Webhook endpoint not found".to_string()));
+        }
+
+        Ok(())
+    }
+
+    // ========================================================================
+    // EVENTS
+    // ========================================================================
+
+    /// Queue an event for the user's endpoints subscribed to it, or for every subscribed
+    /// endpoint when `user_id` is `None` (platform-wide events such as sync.finished).
+    /// Returns the number of deliveries queued.
+    pub async fn emit(
+        &self,
+        user_id: Option<Uuid>,
+        event_type: WebhookEventType,
+        data: serde_json::Value,
+    ) -> Result<u64> {
+        let payload = WebhookEventPayload {
+            event_id: Uuid::new_v4(),
+            event_type,
+            created_at: Utc::now(),
+            data,
+        };
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO webhook_deliveries (endpoint_id, event_id, event_type, payload)
+            SELECT id, $1, $2, $3
+            FROM webhook_endpoints
+            WHERE is_active
+              AND $2 = ANY(event_types)
+              AND ($4::uuid IS NULL OR user_id = $4)
+            "#
+        )
+        .bind(payload.event_id)
+        .bind(event_type.as_str())
+        .bind(serde_json::to_value(&payload)?)
+        .bind(user_id)
+        .execute(&self.db_pool)
+        .await?;
+
+        if result.rows_affected() > 0 {
+            tracing::debug!(
+                "Queued {} webhook deliveries for {} event {}",
+                result.rows_affected(),
+                event_type,
+                payload.event_id
+            );
+        }
+
+        Ok(result.rows_affected())
+    }
+
+    /// `emit` off the request path; failures are logged and never affect the caller
+    pub fn emit_in_background(
+        db_pool: PgPool,
+        user_id: Option<Uuid>,
+        event_type: WebhookEventType,
+        data: serde_json::Value,
+    ) {
+        tokio::spawn(async move {
+            if let Err(e) = OutboundWebhookService::new(db_pool).emit(user_id, event_type, data).await {
+                tracing::error!("Failed to queue {} webhooks: {:?}", event_type, e);
+            }
+        });
+    }
+
+    // ========================================================================
+    // DELIVERY
+    // ========================================================================
+
+    /// Send due deliveries; returns how many were attempted
+    pub async fn process_due_deliveries(&self, batch_size: i64) -> Result<usize> {
+        // Claim a batch by pushing its next attempt out, so a concurrent worker skips it
+        let deliveries = sqlx::query_as::<_, WebhookDelivery>(
+            r#"
+            UPDATE webhook_deliveries SET next_attempt_at = NOW() + INTERVAL '5 minutes'
+            WHERE id IN (
+                SELECT id FROM webhook_deliveries
+                WHERE status = 'pending' AND next_attempt_at <= NOW()
+                ORDER BY next_attempt_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING *
+            "#
+        )
+        .bind(batch_size)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let encryption = encryption_service()?;
+        for delivery in &deliveries {
+            if let Err(e) = self.attempt_delivery(delivery, &encryption).await {
+                tracing::error!("Webhook delivery {} could not be processed: {:?}", delivery.id, e);
+            }
+        }
+
+        Ok(deliveries.len())
+    }
+
+    async fn attempt_delivery(&self, delivery: &WebhookDelivery, encryption: &EncryptionService) -> Result<()> {
+        let endpoint = sqlx::query_as::<_, WebhookEndpoint>("SELECT * FROM webhook_endpoints WHERE id = $1")
+            .bind(delivery.endpoint_id)
+            .fetch_one(&self.db_pool)
+            .await?;
+
+        if !endpoint.is_active {
+            sqlx::query(
+                r#"
+                UPDATE webhook_deliveries
+                SET status = 'failed', next_attempt_at = NULL, error_message = 'Endpoint is disabled'
+                WHERE id = $1
+                "#
+            )
+            .bind(delivery.id)
+            .execute(&self.db_pool)
+            .await?;
+            return Ok(());
+        }
+
+        let secret = encryption.decrypt(&endpoint.secret_encrypted)?;
+        let body = serde_json::to_vec(&delivery.payload)?;
+        let signature = sign_payload(&secret, &body)?;
+        let attempt = delivery.attempt_count + 1;
+
+        let start = Instant::now();
+        let result = self.http_client
+            .post(&endpoint.url)
+            .header("Content-Type", "application/json")
+            .header("User-Agent", "Atlas-Webhooks/1.0")
+            .header("X-Atlas-Event", &delivery.event_type)
+            .header("X-Atlas-Event-Id", delivery.event_id.to_string())
+            .header("X-Atlas-Delivery-Id", delivery.id.to_string())
+            .header("X-Atlas-Delivery-Attempt", attempt.to_string())
+            .header("X-Webhook-Signature", signature)
+            .body(body)
+            .send()
+            .await;
+        let duration_ms = start.elapsed().as_millis() as i32;
+
+        let (status, response_body, error) = match result {
+            Ok(response) => {
+                let status = response.status().as_u16();
+                let text = response.text().await.unwrap_or_default();
+                let body: String = text.chars().take(RESPONSE_BODY_LIMIT).collect();
+                let error = (!(200..300).contains(&status)).then(|| format!("Endpoint returned HTTP {}", status));
+                (Some(status), Some(body), error)
+            }
+            Err(e) => (None, None, Some(format!("Request failed: {}", e))),
+        };
+
+        let succeeded = error.is_none();
+        let next_attempt_at = if succeeded || !is_retryable(status) {
+            None
+        } else {
+            retry_delay(attempt).map(|delay| Utc::now() + delay)
+        };
+        let delivery_status = if succeeded {
+            "succeeded"
+        } else if next_attempt_at.is_some() {
+            "pending"
+        } else {
+            "failed"
+        };
+
+        sqlx::query(
+            r#"
+            UPDATE webhook_deliveries SET
+                status = $2,
+                attempt_count = $3,
+                next_attempt_at = $4,
+                last_attempt_at = NOW(),
+                response_status = $5,
+                response_body = $6,
+                error_message = $7,
+                duration_ms = $8,
+                delivered_at = CASE WHEN $2 = 'succeeded' THEN NOW() ELSE NULL END
+            WHERE id = $1
+            "#
+        )
+        .bind(delivery.id)
+        .bind(delivery_status)
+        .bind(attempt)
+        .bind(next_attempt_at)
+        .bind(status.map(i32::from))
+        .bind(&response_body)
+        .bind(&error)
+        .bind(duration_ms)
+        .execute(&self.db_pool)
+        .await?;
+
+        if succeeded {
+            sqlx::query(
+                "UPDATE webhook_endpoints SET consecutive_failures = 0, last_delivery_at = NOW() WHERE id = $1"
+            )
+            .bind(endpoint.id)
+            .execute(&self.db_pool)
+            .await?;
+        } else {
+            tracing::warn!(
+                "Webhook delivery {} to endpoint {} failed (attempt {}): {}",
+                delivery.id,
+                endpoint.id,
+                attempt,
+                error.as_deref().unwrap_or_default()
+            );
+
+            sqlx::query(
+                r#"
+                UPDATE webhook_endpoints SET
+                    consecutive_failures = consecutive_failures + 1,
+                    is_active = consecutive_failures + 1 < $2,
+                    disabled_reason = CASE
+                        WHEN consecutive_failures + 1 >= $2 THEN 'Disabled after ' || $2 || ' failed deliveries in a row'
+                        ELSE disabled_reason
+                    END,
+                    updated_at = NOW()
+                WHERE id = $1
+                "#
+            )
+            .bind(endpoint.id)
+            .bind(MAX_CONSECUTIVE_FAILURES)
+            .execute(&self.db_pool)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    // ========================================================================
+    // DELIVERY LOG
+    // ========================================================================
+
+    pub async fn list_deliveries(&self, user_id: Uuid, query: &WebhookDeliveriesQuery) -> Result<Vec<WebhookDelivery>> {
+        let deliveries = sqlx::query_as::<_, WebhookDelivery>(
+            r#"
+            SELECT d.*
+            FROM webhook_deliveries d
+            JOIN webhook_endpoints e ON e.id = d.endpoint_id
+            WHERE e.user_id = $1
+              AND ($2::uuid IS NULL OR d.endpoint_id = $2)
+              AND ($3::text IS NULL OR d.status = $3)
+              AND ($4::text IS NULL OR d.event_type = $4)
+            ORDER BY d.created_at DESC
+            LIMIT $5 OFFSET $6
+            "#
+        )
+        .bind(user_id)
+        .bind(query.endpoint_id)
+        .bind(&query.status)
+        .bind(&query.event_type)
+        .bind(query.limit.unwrap_or(50).clamp(1, 200))
+        .bind(query.offset.unwrap_or(0).max(0))
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(deliveries)
+    }
+
+    pub async fn get_delivery(&self, delivery_id: Uuid, user_id: Uuid) -> Result<WebhookDelivery> {
+        sqlx::query_as::<_, WebhookDelivery>(
+            r#"
+            SELECT d.*
+            FROM webhook_deliveries d
+            JOIN webhook_endpoints e ON e.id = d.endpoint_id
+            WHERE d.id = $1 AND e.user_id = $2
+            "#
+        )
+        .bind(delivery_id)
+        .bind(user_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Webhook delivery not found".to_string()))
+    }
+
+    /// Queue a finished delivery again with the same event payload and id
+    pub async fn replay_delivery(&self, delivery_id: Uuid, user_id: Uuid) -> Result<WebhookDelivery> {
+        let original = self.get_delivery(delivery_id, user_id).await?;
+        if original.status == "pending" {
+            return Err(AppError::BadRequest("Delivery is still pending".to_string()));
+        }
+
+        let endpoint = self.get_endpoint(original.endpoint_id, user_id).await?;
+        if !endpoint.is_active {
+            return Err(AppError::BadRequest("Re-enable the endpoint before replaying deliveries".to_string()));
+        }
+
+        let replay = sqlx::query_as::<_, WebhookDelivery>(
+            r#"
+            INSERT INTO webhook_deliveries (endpoint_id, event_id, event_type, payload, replay_of)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING *
+            "#
+        )
+        .bind(original.endpoint_id)
+        .bind(original.event_id)
+        .bind(&original.event_type)
+        .bind(&original.payload)
+        .bind(original.id)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        tracing::info!("Webhook delivery {} replayed as {} by user {}", delivery_id, replay.id, user_id);
+
+        Ok(replay)
+    }
+}
+
+fn validate_endpoint_url(url: &str) -> Result<String> {
+    let url = url.trim();
+    let parsed = url::Url::parse(url)
+        .map_err(|_| AppError::InvalidInput("Invalid webhook url".to_string()))?;
+
+    // 🔒 SECURITY: Only HTTPS endpoints receive signed event payloads
+    let host = parsed.host_str().unwrap_or_default().to_lowercase();
+    if parsed.scheme() != "https" || host.is_empty() {
+        return Err(AppError::InvalidInput("Webhook url must be an https:// URL".to_string()));
+    }
+    // 🔒 SECURITY: No deliveries into our own network
+    if is_internal_host(&parsed) {
+        return Err(AppError::InvalidInput("Webhook url must be publicly reachable".to_string()));
+    }
+
+    Ok(url.to_string())
+}
+
+/// Loopback, private, link-local and unspecified addresses and internal host names,
+/// which outbound webhooks must not target (SSRF)
+pub(crate) fn is_internal_host(url: &url::Url) -> bool {
+    let host = url.host_str().unwrap_or_default().to_lowercase();
+    let is_internal_ip = match url.host() {
+        Some(url::Host::Ipv4(ip)) => is_internal_ip(IpAddr::V4(ip)),
+        Some(url::Host::Ipv6(ip)) => is_internal_ip(IpAddr::V6(ip)),
+        _ => false,
+    };
+    is_internal_ip || host == "localhost" || host.ends_with(".localhost") || host.ends_with(".internal")
+}
+
+pub(crate) fn is_internal_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => ip.is_private() || ip.is_loopback() || ip.is_link_local() || ip.is_unspecified(),
+        IpAddr::V6(ip) => {
+            let first = ip.segments()[0];
+            ip.is_loopback()
+                || ip.is_unspecified()
+                // Unique local (fc00::/7) and link-local (fe80::/10)
+                || (first & 0xfe00) == 0xfc00
+                || (first & 0xffc0) == 0xfe80
+                || ip.to_ipv4_mapped().map_or(false, |v4| is_internal_ip(IpAddr::V4(v4)))
+        }
+    }
+}
+
+/// Whether the host of `url` resolves to an internal address; a host that doesn't
+/// resolve counts as internal. Checked before each delivery, since DNS can change after
+/// the URL was validated.
+pub(crate) async fn resolves_to_internal_address(url: &url::Url) -> bool {
+    let (Some(host), Some(port)) = (url.host_str(), url.port_or_known_default()) else {
+        return true;
+    };
+    // IPv6 literals come bracketed
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    match tokio::net::lookup_host((host, port)).await {
+        Ok(addresses) => {
+            let addresses: Vec<_> = addresses.collect();
+            addresses.is_empty() || addresses.iter().any(|address| is_internal_ip(address.ip()))
+        }
+        Err(e) => {
+            tracing::warn!("Failed to resolve webhook host {}: {}", host, e);
+            true
+        }
+    }
+}
+
+fn event_type_names(event_types: &[WebhookEventType]) -> Result<Vec<String>> {
+    if event_types.is_empty() {
+        return Err(AppError::BadRequest("At least one event type is required".to_string()));
+    }
+
+    let mut names: Vec<String> = event_types.iter().map(|t| t.as_str().to_string()).collect();
+    names.sort();
+    names.dedup();
+    Ok(names)
+}
+
+fn normalize_description(description: Option<String>) -> Result<Option<String>> {
+    let description = description.map(|d| d.trim().to_string()).filter(|d| !d.is_empty());
+    if description.as_ref().map_or(false, |d| d.chars().count() > 255) {
+        return Err(AppError::InvalidInput("description must be at most 255 characters".to_string()));
+    }
+    Ok(description)
+}
+
+fn generate_secret() -> String {
+    use rand::RngCore;
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    format!("whsec_{}", hex::encode(bytes))
+}
+
+fn encryption_service() -> Result<EncryptionService> {
+    let key = std::env::var("ENCRYPTION_KEY")
+        .map_err(|_| AppError::Internal(anyhow::anyhow!("ENCRYPTION_KEY not set")))?;
+    Ok(EncryptionService::new(&key)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endpoint_url_validation() {
+        assert_eq!(
+            validate_endpoint_url(" https://hooks.example.com/atlas ").unwrap(),
+            "https://hooks.example.com/atlas"
+        );
+        assert!(validate_endpoint_url("http://hooks.example.com/atlas").is_err());
+        assert!(validate_endpoint_url("https://localhost/hook").is_err());
+        assert!(validate_endpoint_url("https://10.0.0.5/hook").is_err());
+        assert!(validate_endpoint_url("https://169.254.169.254/latest").is_err());
+        assert!(validate_endpoint_url("https://[fd00::1]/hook").is_err());
+        assert!(validate_endpoint_url("https://[::ffff:127.0.0.1]/hook").is_err());
+        assert!(validate_endpoint_url("not a url").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_resolved_internal_addresses_are_refused() {
+        let url = |u: &str| url::Url::parse(u).unwrap();
+        assert!(resolves_to_internal_address(&url("https://127.0.0.1/hook")).await);
+        assert!(resolves_to_internal_address(&url("https://[::1]:8443/hook")).await);
+        assert!(resolves_to_internal_address(&url("https://10.1.2.3/hook")).await);
+        assert!(!resolves_to_internal_address(&url("https://93.184.216.34/hook")).await);
+        assert!(!is_internal_ip("2606:4700::1111".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_event_type_names_are_deduplicated() {
+        let names = event_type_names(&[
+            WebhookEventType::SyncFinished,
+            WebhookEventType::InquiryCreated,
+            WebhookEventType::SyncFinished,
+        ])
+        .unwrap();
+        assert_eq!(names, vec!["inquiry.created", "sync.finished"]);
+        assert!(event_type_names(&[]).is_err());
+    }
+}
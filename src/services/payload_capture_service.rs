@@ -0,0 +1,255 @@
+/// Payload Capture Service
+///
+/// Superadmins open capture rules for fraud and compliance investigations: one user,
+/// one route or both, for at most a week. Matching requests are stored with their
+/// PII-redacted request and response bodies (see `payload_capture_middleware`) and
+/// deleted once the rule's retention period ends.
+///
+/// Active rules are cached per process so unmatched requests never touch the
+/// database. The instance that changes a rule reloads at once; the scheduler reloads
+/// every minute, which is how rule changes reach the other instances, and purges
+/// expired captures.
+
+use chrono::{Duration as ChronoDuration, Utc};
+use once_cell::sync::Lazy;
+use sqlx::PgPool;
+use std::{sync::RwLock, time::Duration};
+use uuid::Uuid;
+use crate::{
+    middleware::error_handling::{AppError, Result},
+    models::payload_capture::*,
+};
+
+/// Rules that were active at the last reload
+static ACTIVE_RULES: Lazy<RwLock<Vec<PayloadCaptureRule>>> = Lazy::new(|| RwLock::new(Vec::new()));
+
+pub struct PayloadCaptureService {
+    db_pool: PgPool,
+}
+
+impl PayloadCaptureService {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self { db_pool }
+    }
+
+    /// Cheap check before looking at a request at all
+    pub fn has_active_rules() -> bool {
+        !ACTIVE_RULES.read().unwrap_or_else(|e| e.into_inner()).is_empty()
+    }
+
+    /// The first active rule that captures this request
+    pub fn matching_rule(user_id: Option<Uuid>, method: &str, route: &str, path: &str) -> Option<PayloadCaptureRule> {
+        let now = Utc::now();
+        ACTIVE_RULES
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .find(|rule| rule.is_active(now) && rule.matches(user_id, method, route, path))
+            .cloned()
+    }
+
+    pub async fn reload_rules(&self) -> Result<()> {
+        let rules = sqlx::query_as::<_, PayloadCaptureRule>(
+            "SELECT * FROM payload_capture_rules WHERE revoked_at IS NULL AND capture_until > NOW() ORDER BY created_at"
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        *ACTIVE_RULES.write().unwrap_or_else(|e| e.into_inner()) = rules;
+        Ok(())
+    }
+
+    // ========================================================================
+    // RULES
+    // ========================================================================
+
+    pub async fn list_rules(&self) -> Result<Vec<PayloadCaptureRule>> {
+        let rules = sqlx::query_as::<_, PayloadCaptureRule>(
+            "SELECT * FROM payload_capture_rules ORDER BY created_at DESC LIMIT 200"
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(rules)
+    }
+
+    pub async fn create_rule(&self, request: &CreatePayloadCaptureRuleRequest, admin_id: Uuid) -> Result<PayloadCaptureRule> {
+        let rule = request.validate(Utc::now()).map_err(AppError::BadRequest)?;
+
+        if let Some(user_id) = rule.user_id {
+            let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM users WHERE id = $1)")
+                .bind(user_id)
+                .fetch_one(&self.db_pool)
+                .await?;
+            if !exists {
+                return Err(AppError::NotFound("User not found".to_string()));
+            }
+        }
+
+        let rule = sqlx::query_as::<_, PayloadCaptureRule>(
+            r#"
+            INSERT INTO payload_capture_rules
+                (user_id, route, method, reason, retention_days, created_by, capture_until)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING *
+            "#
+        )
+        .bind(rule.user_id)
+        .bind(&rule.route)
+        .bind(&rule.method)
+        .bind(&rule.reason)
+        .bind(rule.retention_days)
+        .bind(admin_id)
+        .bind(rule.capture_until)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        self.reload_rules().await?;
+        tracing::warn!(
+            "🎙️  Payload capture rule {} opened by {} until {}",
+            rule.id,
+            admin_id,
+            rule.capture_until
+        );
+
+        Ok(rule)
+    }
+
+    /// Stop capturing; captures already stored are kept until they expire
+    pub async fn revoke_rule(&self, rule_id: Uuid, admin_id: Uuid) -> Result<PayloadCaptureRule> {
+        let rule = sqlx::query_as::<_, PayloadCaptureRule>(
+            r#"
+            UPDATE payload_capture_rules
+            SET revoked_at = NOW(), revoked_by = $2
+            WHERE id = $1 AND revoked_at IS NULL
+            RETURNING *
+            "#
+        )
+        .bind(rule_id)
+        .bind(admin_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("No open capture rule with this id".to_string()))?;
+
+        self.reload_rules().await?;
+        tracing::info!("Payload capture rule {} revoked by {}", rule.id, admin_id);
+
+        Ok(rule)
+    }
+
+    // ========================================================================
+    // CAPTURES
+    // ========================================================================
+
+    pub async fn record(&self, capture: &NewPayloadCapture) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO payload_captures
+                (rule_id, request_id, user_id, method, route, path, query, status, latency_ms,
+                 request_content_type, request_body, response_content_type, response_body,
+                 truncated, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+            "#
+        )
+        .bind(capture.rule_id)
+        .bind(capture.request_id)
+        .bind(capture.user_id)
+        .bind(&capture.method)
+        .bind(&capture.route)
+        .bind(&capture.path)
+        .bind(&capture.query)
+        .bind(capture.status as i16)
+        .bind(capture.latency_ms)
+        .bind(&capture.request_content_type)
+        .bind(&capture.request_body)
+        .bind(&capture.response_content_type)
+        .bind(&capture.response_body)
+        .bind(capture.truncated)
+        .bind(Utc::now() + ChronoDuration::days(capture.retention_days as i64))
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Captures without their bodies, newest first
+    pub async fn list_captures(&self, query: &PayloadCaptureQuery) -> Result<Vec<PayloadCaptureSummary>> {
+        let captures = sqlx::query_as::<_, PayloadCaptureSummary>(
+            r#"
+            SELECT id, rule_id, request_id, user_id, method, route, status, latency_ms,
+                   truncated, captured_at, expires_at
+            FROM payload_captures
+            WHERE ($1::UUID IS NULL OR rule_id = $1)
+              AND ($2::UUID IS NULL OR user_id = $2)
+              AND expires_at > NOW()
+            ORDER BY captured_at DESC
+            LIMIT $3 OFFSET $4
+            "#
+        )
+        .bind(query.rule_id)
+        .bind(query.user_id)
+        .bind(query.limit.unwrap_or(50).clamp(1, 200))
+        .bind(query.offset.unwrap_or(0).max(0))
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(captures)
+    }
+
+    pub async fn get_capture(&self, capture_id: Uuid) -> Result<PayloadCapture> {
+        sqlx::query_as::<_, PayloadCapture>(
+            "SELECT * FROM payload_captures WHERE id = $1 AND expires_at > NOW()"
+        )
+        .bind(capture_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Capture not found".to_string()))
+    }
+
+    pub async fn purge_expired(&self) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM payload_captures WHERE expires_at <= NOW()")
+            .execute(&self.db_pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+// ============================================================================
+// Scheduler
+// ============================================================================
+
+pub struct PayloadCaptureScheduler {
+    pool: PgPool,
+}
+
+impl PayloadCaptureScheduler {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn run(&self) {
+        let mut ticker = tokio::time::interval(Duration::from_secs(60));
+        let service = PayloadCaptureService::new(self.pool.clone());
+
+        loop {
+            ticker.tick().await;
+
+            // Picks up rules opened or revoked on other instances, and drops ended ones
+            if let Err(e) = service.reload_rules().await {
+                tracing::error!("Payload capture rule reload failed: {:?}", e);
+            }
+
+            if crate::services::MaintenanceService::pauses("payload_capture_purge")
+                || !crate::services::SchedulerLeader::leads("payload_capture_purge").await
+            {
+                continue;
+            }
+            match service.purge_expired().await {
+                Ok(0) => {}
+                Ok(count) => tracing::info!("Purged {} expired payload captures", count),
+                Err(e) => tracing::error!("Payload capture purge failed: {:?}", e),
+            }
+        }
+    }
+}
@@ -1,6 +1,12 @@
 use uuid::Uuid;
-use crate::models::pharmaceutical::{Pharmaceutical, CreatePharmaceuticalRequest, SearchPharmaceuticalRequest, PharmaceuticalResponse};
-use crate::repositories::PharmaceuticalRepository;
+use crate::models::pharmaceutical::{Pharmaceutical, CreatePharmaceuticalRequest, SearchPharmaceuticalRequest, PharmaceuticalResponse, ProductDomain};
+use crate::models::ingredient::{
+    parse_ingredient_list, IngredientRole, ParsedIngredient, PharmaceuticalIngredientsResponse, SetIngredientsRequest,
+};
+use crate::models::summary_aggregate::Summarized;
+use crate::models::openfda::OpenFdaCatalogEntry;
+use crate::repositories::{OpenFdaRepository, PharmaceuticalRepository};
+use std::collections::HashMap;
 use crate::middleware::error_handling::{Result, AppError};
 
 pub struct PharmaService {
@@ -12,7 +18,16 @@ impl PharmaService {
         Self { pharma_repo }
     }
 
-    pub async fn create_pharmaceutical(&self, request: CreatePharmaceuticalRequest) -> Result<PharmaceuticalResponse> {
+    pub async fn create_pharmaceutical(&self, mut request: CreatePharmaceuticalRequest) -> Result<PharmaceuticalResponse> {
+        validate_product_domain(&mut request)?;
+
+        // Devices are identified by their GUDID device identifier rather than an NDC
+        if let Some(ref udi_di) = request.udi_di {
+            if let Some(existing) = self.pharma_repo.find_by_udi_di(udi_di).await? {
+                return Ok(existing.into());
+            }
+        }
+
         // If NDC provided, use find-or-create pattern to avoid duplicates
         if let Some(ref ndc_code) = request.ndc_code {
             // First, check if it already exists
@@ -36,6 +51,11 @@ impl PharmaService {
                                     return Ok(existing.into());
                                 }
                             }
+                            if let Some(ref udi_di) = request.udi_di {
+                                if let Some(existing) = self.pharma_repo.find_by_udi_di(udi_di).await? {
+                                    return Ok(existing.into());
+                                }
+                            }
                             // If we can't find it, return conflict error
                             return Err(AppError::Conflict);
                         }
@@ -61,11 +81,11 @@ impl PharmaService {
         Ok(pharmaceuticals.into_iter().map(Into::into).collect())
     }
 
-    pub async fn get_manufacturers(&self) -> Result<Vec<String>> {
+    pub async fn get_manufacturers(&self) -> Result<Summarized<Vec<String>>> {
         self.pharma_repo.get_manufacturers().await
     }
 
-    pub async fn get_categories(&self) -> Result<Vec<String>> {
+    pub async fn get_categories(&self) -> Result<Summarized<Vec<String>>> {
         self.pharma_repo.get_categories().await
     }
 
@@ -78,8 +98,218 @@ impl PharmaService {
         Ok(pharma.into())
     }
 
+    pub async fn get_ingredients(&self, pharmaceutical_id: Uuid) -> Result<PharmaceuticalIngredientsResponse> {
+        self.pharma_repo
+            .find_by_id(pharmaceutical_id)
+            .await?
+            .ok_or(AppError::NotFound("Resource not found".to_string()))?;
+
+        let ingredients = self.pharma_repo.get_ingredients(pharmaceutical_id).await?;
+        let excipients_declared = self.pharma_repo.ingredients_declared(pharmaceutical_id).await?;
+
+        let mut allergens: Vec<String> = ingredients.iter().flat_map(|i| i.allergens.clone()).collect();
+        allergens.sort();
+        allergens.dedup();
+
+        let (active_ingredients, inactive_ingredients) = ingredients
+            .into_iter()
+            .partition(|i| i.role == IngredientRole::Active.as_str());
+
+        Ok(PharmaceuticalIngredientsResponse {
+            pharmaceutical_id,
+            active_ingredients,
+            inactive_ingredients,
+            excipients_declared,
+            allergens,
+        })
+    }
+
+    pub async fn set_ingredients(
+        &self,
+        pharmaceutical_id: Uuid,
+        request: SetIngredientsRequest,
+    ) -> Result<PharmaceuticalIngredientsResponse> {
+        self.pharma_repo
+            .find_by_id(pharmaceutical_id)
+            .await?
+            .ok_or(AppError::NotFound("Resource not found".to_string()))?;
+
+        let mut ingredients: Vec<ParsedIngredient> = request
+            .active_ingredients
+            .iter()
+            .map(|i| ParsedIngredient::new(&i.name, i.strength.clone(), IngredientRole::Active))
+            .collect();
+
+        // Only a request that lists the excipients (even as an empty list) declares them
+        let excipients_declared =
+            request.inactive_ingredients.is_some() || request.inactive_ingredients_text.is_some();
+        match (request.inactive_ingredients, request.inactive_ingredients_text) {
+            (Some(_), Some(_)) => {
+                return Err(AppError::BadRequest(
+                    "Provide either inactive_ingredients or inactive_ingredients_text, not both".to_string(),
+                ));
+            }
+            (Some(list), None) => ingredients.extend(
+                list.iter().map(|i| ParsedIngredient::new(&i.name, i.strength.clone(), IngredientRole::Inactive)),
+            ),
+            (None, Some(text)) => ingredients.extend(parse_ingredient_list(&text, IngredientRole::Inactive)),
+            (None, None) => {}
+        }
+
+        if ingredients.iter().any(|i| i.name.is_empty() || i.name.chars().count() > 255) {
+            return Err(AppError::InvalidInput("Ingredient names must be 1-255 characters".to_string()));
+        }
+
+        if ingredients.len() > 200 {
+            return Err(AppError::InvalidInput("At most 200 ingredients per product".to_string()));
+        }
+
+        self.pharma_repo.set_ingredients(pharmaceutical_id, &ingredients, excipients_declared).await?;
+        self.get_ingredients(pharmaceutical_id).await
+    }
+
     pub async fn validate_pharmaceutical_exists(&self, id: Uuid) -> Result<bool> {
         let pharma = self.pharma_repo.find_by_id(id).await?;
         Ok(pharma.is_some())
     }
-}
\ No newline at end of file
+}
+
+/// Embed the OpenFDA catalog entry of each product whose NDC is in the catalog
+/// (`expand=catalog_entry`), with one catalog query for all of them
+pub async fn expand_catalog_entries(
+    catalog: &OpenFdaRepository,
+    pharmaceuticals: Vec<&mut PharmaceuticalResponse>,
+) -> Result<()> {
+    let mut product_ndcs: Vec<String> = pharmaceuticals.iter().filter_map(|pharma| pharma.product_ndc()).collect();
+    if product_ndcs.is_empty() {
+        return Ok(());
+    }
+    product_ndcs.sort();
+    product_ndcs.dedup();
+
+    let entries: HashMap<String, OpenFdaCatalogEntry> = catalog
+        .find_by_ndcs(&product_ndcs)
+        .await?
+        .into_iter()
+        .map(|entry| (entry.product_ndc.clone(), entry))
+        .collect();
+    for pharma in pharmaceuticals {
+        pharma.catalog_entry = pharma.product_ndc().and_then(|ndc| entries.get(&ndc).cloned());
+    }
+    Ok(())
+}
+
+/// Enforce the identifiers each catalog segment uses: devices carry a UDI-DI and
+/// device class instead of an NDC; veterinary products may list target species.
+fn validate_product_domain(request: &mut CreatePharmaceuticalRequest) -> Result<()> {
+    let domain = request.product_domain.unwrap_or_default();
+
+    request.udi_di = request.udi_di.take().map(|d| d.trim().to_string()).filter(|d| !d.is_empty());
+    request.device_class = request.device_class.take().map(|c| c.trim().to_uppercase()).filter(|c| !c.is_empty());
+
+    match domain {
+        ProductDomain::MedicalDevice => {
+            let udi_di = request.udi_di.as_deref().ok_or_else(|| {
+                AppError::InvalidInput("Medical devices require a udi_di (GUDID device identifier)".to_string())
+            })?;
+            if !udi_di.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '$' | '/' | '-' | '.' | '=')) {
+                return Err(AppError::InvalidInput("udi_di contains invalid characters".to_string()));
+            }
+            if request.ndc_code.is_some() {
+                return Err(AppError::InvalidInput("Medical devices are identified by udi_di, not ndc_code".to_string()));
+            }
+            if let Some(ref class) = request.device_class {
+                if !matches!(class.as_str(), "1" | "2" | "3" | "U" | "N" | "F") {
+                    return Err(AppError::InvalidInput("device_class must be one of 1, 2, 3, U, N, F".to_string()));
+                }
+            }
+            if request.target_species.is_some() {
+                return Err(AppError::InvalidInput("target_species only applies to veterinary products".to_string()));
+            }
+        }
+        ProductDomain::HumanDrug | ProductDomain::Veterinary => {
+            if request.udi_di.is_some() || request.device_class.is_some() {
+                return Err(AppError::InvalidInput(
+                    "udi_di and device_class only apply to medical devices".to_string(),
+                ));
+            }
+            if domain == ProductDomain::HumanDrug && request.target_species.is_some() {
+                return Err(AppError::InvalidInput("target_species only applies to veterinary products".to_string()));
+            }
+        }
+    }
+
+    request.product_domain = Some(domain);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(domain: ProductDomain) -> CreatePharmaceuticalRequest {
+        CreatePharmaceuticalRequest {
+            brand_name: "SureFlow".to_string(),
+            generic_name: "Infusion set".to_string(),
+            ndc_code: None,
+            manufacturer: "Acme Medical".to_string(),
+            category: None,
+            description: None,
+            strength: None,
+            dosage_form: None,
+            storage_requirements: None,
+            product_domain: Some(domain),
+            udi_di: None,
+            device_class: None,
+            target_species: None,
+        }
+    }
+
+    #[test]
+    fn test_devices_need_a_udi_di_instead_of_an_ndc() {
+        let mut device = request(ProductDomain::MedicalDevice);
+        device.udi_di = Some(" 00884521062859 ".to_string());
+        device.device_class = Some(" u ".to_string());
+        validate_product_domain(&mut device).unwrap();
+        assert_eq!(device.udi_di.as_deref(), Some("00884521062859"));
+        assert_eq!(device.device_class.as_deref(), Some("U"));
+
+        let mut missing = request(ProductDomain::MedicalDevice);
+        missing.udi_di = Some("  ".to_string());
+        assert!(validate_product_domain(&mut missing).is_err());
+
+        let mut with_ndc = request(ProductDomain::MedicalDevice);
+        with_ndc.udi_di = Some("00884521062859".to_string());
+        with_ndc.ndc_code = Some("0002-1433-80".to_string());
+        assert!(validate_product_domain(&mut with_ndc).is_err());
+
+        let mut bad_class = request(ProductDomain::MedicalDevice);
+        bad_class.udi_di = Some("00884521062859".to_string());
+        bad_class.device_class = Some("4".to_string());
+        assert!(validate_product_domain(&mut bad_class).is_err());
+
+        let mut bad_chars = request(ProductDomain::MedicalDevice);
+        bad_chars.udi_di = Some("0088 4521".to_string());
+        assert!(validate_product_domain(&mut bad_chars).is_err());
+    }
+
+    #[test]
+    fn test_device_and_species_fields_stay_in_their_domain() {
+        let mut drug = request(ProductDomain::HumanDrug);
+        drug.product_domain = None;
+        validate_product_domain(&mut drug).unwrap();
+        assert_eq!(drug.product_domain, Some(ProductDomain::HumanDrug));
+
+        let mut drug_with_udi = request(ProductDomain::HumanDrug);
+        drug_with_udi.udi_di = Some("00884521062859".to_string());
+        assert!(validate_product_domain(&mut drug_with_udi).is_err());
+
+        let mut drug_with_species = request(ProductDomain::HumanDrug);
+        drug_with_species.target_species = Some(vec!["canine".to_string()]);
+        assert!(validate_product_domain(&mut drug_with_species).is_err());
+
+        let mut veterinary = request(ProductDomain::Veterinary);
+        veterinary.target_species = Some(vec!["canine".to_string()]);
+        assert!(validate_product_domain(&mut veterinary).is_ok());
+    }
+}
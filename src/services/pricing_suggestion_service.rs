@@ -0,0 +1,491 @@
+/// AI Pricing Suggestion Service - listing prices from market data plus LLM reasoning
+///
+/// Builds a market snapshot for an NDC (marketplace listings and transaction history,
+/// recalls, FDA listing and EMA authorization status), asks Claude for a unit price
+/// with rationale and confidence, then keeps the price within plausible market bounds
+/// and the confidence within what the amount of market evidence supports.
+
+use chrono::{Duration, NaiveDate, Utc};
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::{
+    middleware::error_handling::{Result, AppError},
+    models::openfda_recall::extract_ndcs,
+    models::pricing_suggestion::*,
+    services::claude_ai_service::{ClaudeAIService, ClaudeRequestConfig, user_message},
+    services::ai_response_cache_service::AiFeature,
+    services::openfda_recall_service::OpenFdaRecallService,
+};
+
+/// Transactions older than this are not considered comparable
+const TRANSACTION_LOOKBACK_DAYS: i64 = 180;
+const TREND_MONTHS: i32 = 12;
+
+const SYSTEM_PROMPT: &str = r#"You are a pricing analyst for a B2B pharmaceutical marketplace where licensed pharmacies, distributors and manufacturers trade surplus and short-dated stock.
+
+Your task is to suggest a competitive unit price for a listing, given market data for the product.
+
+PRICING PRINCIPLES:
+1. Anchor on comparable marketplace listings with a similar expiry window, then recent transaction prices
+2. Short-dated stock sells at a discount: the closer to expiry, the steeper the discount
+3. Large quantities may justify a volume discount relative to typical transaction sizes
+4. Active shortages, recalls of other lots, or withdrawn competing authorizations can support a premium; never price-gouge
+5. An expired FDA listing or a suspended/withdrawn authorization is a compliance risk: mention it and lower confidence
+6. When market data is thin, say so and keep confidence low
+
+RESPONSE FORMAT:
+Return ONLY a JSON object:
+{
+  "suggested_unit_price": 12.34,
+  "price_range_low": 11.50,
+  "price_range_high": 13.00,
+  "confidence": 0.0-1.0,
+  "rationale": "2-4 sentences explaining the price, citing the market data it is based on"
+}
+
+Prices are in USD per unit as plain numbers without currency symbols."#;
+
+pub struct PricingSuggestionService {
+    db_pool: PgPool,
+    claude_service: ClaudeAIService,
+}
+
+impl PricingSuggestionService {
+    pub fn new(db_pool: PgPool, claude_api_key: String) -> Self {
+        let claude_service = ClaudeAIService::new(claude_api_key, db_pool.clone());
+        Self {
+            db_pool,
+            claude_service,
+        }
+    }
+
+    /// Suggest a listing price for an NDC, expiry window and quantity
+    pub async fn suggest_price(
+        &self,
+        user_id: Uuid,
+        request: PricingSuggestionRequest,
+    ) -> Result<AiPricingSuggestion> {
+        let ndc_code = request.ndc_code.trim().to_string();
+        if extract_ndcs(&ndc_code).is_empty() {
+            return Err(AppError::BadRequest(format!("Invalid NDC: {}", ndc_code)));
+        }
+        if request.quantity <= 0 {
+            return Err(AppError::BadRequest("Quantity must be positive".to_string()));
+        }
+        let expiry_to = request.expiry_to.unwrap_or(request.expiry_from);
+        if expiry_to < request.expiry_from {
+            return Err(AppError::BadRequest("expiry_to must not be before expiry_from".to_string()));
+        }
+        if expiry_to < Utc::now().date_naive() {
+            return Err(AppError::BadRequest("Expired stock cannot be listed".to_string()));
+        }
+
+        // 1. Check quota
+        if !self.check_pricing_quota(user_id).await? {
+            return Err(AppError::QuotaExceeded(
+                "Monthly AI pricing suggestion limit reached. Please upgrade your plan or wait for reset.".to_string()
+            ));
+        }
+        if !self.claude_service.check_user_quota(user_id).await? {
+            return Err(AppError::QuotaExceeded(
+                "Monthly AI usage limit exceeded. Please upgrade your plan or wait for reset.".to_string()
+            ));
+        }
+
+        // 2. Gather market context
+        let context = self
+            .load_market_context(&ndc_code, request.expiry_from, expiry_to, request.quantity, user_id)
+            .await?;
+
+        // 3. Call Claude
+        let config = ClaudeRequestConfig {
+            max_tokens: 1024,
+            temperature: Some(0.2), // Prices should be reproducible for the same market data
+            system_prompt: Some(SYSTEM_PROMPT.to_string()),
+        };
+
+        let suggestion_id = Uuid::new_v4();
+        let validated = self.claude_service.send_message_validated::<AiPricingResponse>(
+            vec![user_message(build_prompt(&context, request.expiry_from, expiry_to))],
+            config,
+            user_id,
+            Some(suggestion_id),
+            AiFeature::PricingSuggestion,
+        ).await?;
+        let claude_response = validated.response;
+        let ai_response = validated.value;
+
+        // 4. Apply guardrails
+
+        let reference = context.reference_prices();
+        let guarded = guard_price(
+            ai_response.suggested_unit_price,
+            ai_response.price_range_low,
+            ai_response.price_range_high,
+            reference,
+        )
+        .ok_or_else(|| AppError::Internal(anyhow::anyhow!("AI suggested an invalid price")))?;
+        self.claude_service.cache_response(&claude_response).await;
+
+        let mut confidence = ai_response.confidence.clamp(0.0, 1.0).min(confidence_cap(context.data_points()));
+        let mut rationale = ai_response.rationale.trim().to_string();
+        if guarded.clamped {
+            confidence = confidence.min(0.5);
+            rationale.push_str(&format!(
+                " (Adjusted from ${:.2} to stay within the observed market range.)",
+                ai_response.suggested_unit_price
+            ));
+        }
+
+        // 5. Save suggestion with its market snapshot
+        let suggestion = sqlx::query_as::<_, AiPricingSuggestion>(
+            r#"
+            INSERT INTO ai_pricing_suggestions (
+                id, user_id, ndc_code, expiry_from, expiry_to, quantity,
+                suggested_unit_price, price_range_low, price_range_high, confidence, rationale,
+                market_snapshot, ai_cost_usd, ai_tokens_used
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+            RETURNING *
+            "#
+        )
+        .bind(suggestion_id)
+        .bind(user_id)
+        .bind(&ndc_code)
+        .bind(request.expiry_from)
+        .bind(expiry_to)
+        .bind(request.quantity)
+        .bind(guarded.price)
+        .bind(guarded.low)
+        .bind(guarded.high)
+        .bind(Decimal::from_f64(confidence).unwrap_or_default().round_dp(3))
+        .bind(&rationale)
+        .bind(serde_json::to_value(&context)?)
+        .bind(Decimal::try_from(claude_response.cost_usd).unwrap_or_default())
+        .bind((claude_response.input_tokens + claude_response.output_tokens) as i32)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        // 6. Track usage
+        sqlx::query(
+            r#"
+            INSERT INTO user_ai_usage_limits (user_id, monthly_pricing_suggestions_used)
+            VALUES ($1, 1)
+            ON CONFLICT (user_id)
+            DO UPDATE SET
+                monthly_pricing_suggestions_used = user_ai_usage_limits.monthly_pricing_suggestions_used + 1,
+                updated_at = NOW()
+            "#
+        )
+        .bind(user_id)
+        .execute(&self.db_pool)
+        .await?;
+
+        self.claude_service.increment_user_usage(user_id, claude_response.cost_usd).await?;
+
+        tracing::info!(
+            "Pricing suggestion generated: ndc={}, user={}, price={}, confidence={:.2}, data_points={}, cost=${}",
+            ndc_code,
+            user_id,
+            guarded.price,
+            confidence,
+            context.data_points(),
+            claude_response.cost_usd
+        );
+
+        Ok(suggestion)
+    }
+
+    /// Market data the suggestion is based on; the user's own listings are excluded
+    async fn load_market_context(
+        &self,
+        ndc_code: &str,
+        expiry_from: NaiveDate,
+        expiry_to: NaiveDate,
+        quantity: i32,
+        user_id: Uuid,
+    ) -> Result<PricingMarketContext> {
+        let product = sqlx::query_as::<_, (String, String, String, Option<String>, Option<String>)>(
+            r#"
+            SELECT brand_name, generic_name, manufacturer, strength, dosage_form
+            FROM pharmaceuticals
+            WHERE REPLACE(ndc_code, '-', '') = REPLACE($1, '-', '')
+            LIMIT 1
+            "#
+        )
+        .bind(ndc_code)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        let (product_ndc, _) = extract_ndcs(ndc_code)
+            .into_iter()
+            .next()
+            .ok_or_else(|| AppError::BadRequest(format!("Invalid NDC: {}", ndc_code)))?;
+
+        let fda_listing = sqlx::query_as::<_, (String, String, String, Option<String>, Option<String>, Option<String>, Option<NaiveDate>)>(
+            r#"
+            SELECT brand_name, generic_name, labeler_name, strength, dosage_form,
+                   marketing_category, listing_expiration_date
+            FROM openfda_catalog
+            WHERE product_ndc = $1
+            "#
+        )
+        .bind(&product_ndc)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        if product.is_none() && fda_listing.is_none() {
+            return Err(AppError::NotFound(format!(
+                "NDC {} is not in the marketplace or the FDA catalog",
+                ndc_code
+            )));
+        }
+
+        let listings = sqlx::query_as::<_, (Decimal, NaiveDate, i32)>(
+            r#"
+            SELECT i.unit_price, i.expiry_date, i.quantity
+            FROM inventory i
+            JOIN pharmaceuticals p ON p.id = i.pharmaceutical_id
+            WHERE REPLACE(p.ndc_code, '-', '') = REPLACE($1, '-', '')
+              AND i.status = 'available'
+              AND i.unit_price IS NOT NULL
+              AND i.quantity > 0
+              AND i.expiry_date >= CURRENT_DATE
+              AND i.user_id != $2
+            "#
+        )
+        .bind(ndc_code)
+        .bind(user_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let transactions = sqlx::query_as::<_, (Decimal, i32)>(
+            r#"
+            SELECT t.unit_price, t.quantity
+            FROM transactions t
+            JOIN inquiries q ON q.id = t.inquiry_id
+            JOIN inventory i ON i.id = q.inventory_id
+            JOIN pharmaceuticals p ON p.id = i.pharmaceutical_id
+            WHERE REPLACE(p.ndc_code, '-', '') = REPLACE($1, '-', '')
+              AND t.status IN ('completed', 'pending')
+              AND t.transaction_date >= $2
+            "#
+        )
+        .bind(ndc_code)
+        .bind(Utc::now() - Duration::days(TRANSACTION_LOOKBACK_DAYS))
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let monthly_trend = sqlx::query_as::<_, MonthlyPrice>(
+            r#"
+            SELECT
+                date_trunc('month', t.transaction_date)::date AS month,
+                (percentile_cont(0.5) WITHIN GROUP (ORDER BY t.unit_price))::numeric(12, 2) AS median_unit_price,
+                COUNT(*) AS transactions
+            FROM transactions t
+            JOIN inquiries q ON q.id = t.inquiry_id
+            JOIN inventory i ON i.id = q.inventory_id
+            JOIN pharmaceuticals p ON p.id = i.pharmaceutical_id
+            WHERE REPLACE(p.ndc_code, '-', '') = REPLACE($1, '-', '')
+              AND t.status = 'completed'
+              AND t.transaction_date >= date_trunc('month', NOW()) - make_interval(months => $2)
+            GROUP BY 1
+            ORDER BY 1
+            "#
+        )
+        .bind(ndc_code)
+        .bind(TREND_MONTHS)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        // Open recalls, plus closed ones from the last two years
+        let recall_cutoff = Utc::now().date_naive() - Duration::days(730);
+        let recalls = OpenFdaRecallService::new(self.db_pool.clone())
+            .recalls_for_ndc(ndc_code)
+            .await?
+            .into_iter()
+            .filter(|r| {
+                r.status.as_deref() != Some("Terminated")
+                    || r.report_date.map(|d| d >= recall_cutoff).unwrap_or(false)
+            })
+            .map(|r| RecallSignal {
+                recall_number: r.recall_number,
+                classification: r.classification,
+                status: r.status,
+                report_date: r.report_date,
+                reason_for_recall: r.reason_for_recall,
+            })
+            .collect();
+
+        let generic_name = product
+            .as_ref()
+            .map(|p| p.1.clone())
+            .or_else(|| fda_listing.as_ref().map(|f| f.1.clone()));
+
+        let ema_authorization_statuses: Vec<String> = match &generic_name {
+            Some(name) => sqlx::query_scalar(
+                r#"
+                SELECT DISTINCT authorization_status
+                FROM ema_catalog
+                WHERE LOWER(inn_name) = LOWER($1) AND authorization_status IS NOT NULL
+                ORDER BY authorization_status
+                "#
+            )
+            .bind(name)
+            .fetch_all(&self.db_pool)
+            .await?,
+            None => Vec::new(),
+        };
+
+        let in_window: Vec<Decimal> = listings
+            .iter()
+            .filter(|(_, expiry, _)| *expiry >= expiry_from && *expiry <= expiry_to)
+            .map(|(price, _, _)| *price)
+            .collect();
+        let all_prices: Vec<Decimal> = listings.iter().map(|(price, _, _)| *price).collect();
+        let transaction_prices: Vec<Decimal> = transactions.iter().map(|(price, _)| *price).collect();
+
+        let mut transaction_quantities: Vec<i32> = transactions.iter().map(|(_, qty)| *qty).collect();
+        transaction_quantities.sort_unstable();
+        let median_transaction_quantity = transaction_quantities.get(transaction_quantities.len() / 2).copied();
+
+        let (brand_name, manufacturer, strength, dosage_form) = match (&product, &fda_listing) {
+            (Some(p), _) => (Some(p.0.clone()), Some(p.2.clone()), p.3.clone(), p.4.clone()),
+            (None, Some(f)) => (Some(f.0.clone()), Some(f.2.clone()), f.3.clone(), f.4.clone()),
+            (None, None) => (None, None, None, None),
+        };
+
+        Ok(PricingMarketContext {
+            ndc_code: ndc_code.to_string(),
+            brand_name,
+            generic_name,
+            manufacturer,
+            strength,
+            dosage_form,
+            days_to_expiry: (expiry_from - Utc::now().date_naive()).num_days(),
+            quantity,
+            listings_in_window: PriceStats::from_prices(&in_window),
+            listings_all: PriceStats::from_prices(&all_prices),
+            listed_units: listings.iter().map(|(_, _, qty)| *qty as i64).sum(),
+            recent_transactions: PriceStats::from_prices(&transaction_prices),
+            median_transaction_quantity,
+            monthly_trend,
+            recalls,
+            fda_listing_expired: fda_listing
+                .as_ref()
+                .and_then(|f| f.6)
+                .map(|d| d < Utc::now().date_naive())
+                .unwrap_or(false),
+            fda_marketing_category: fda_listing.and_then(|f| f.5),
+            ema_authorization_statuses,
+        })
+    }
+
+    /// Whether the user has pricing suggestions left this month
+    async fn check_pricing_quota(&self, user_id: Uuid) -> Result<bool> {
+        let has_quota: Option<bool> = sqlx::query_scalar(
+            r#"
+            SELECT monthly_pricing_suggestions_used < monthly_pricing_suggestion_limit
+            FROM user_ai_usage_limits
+            WHERE user_id = $1
+            "#
+        )
+        .bind(user_id)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        // If no limits set, allow (defaults will be applied on first use)
+        Ok(has_quota.unwrap_or(true))
+    }
+
+    /// Get a suggestion owned by the user
+    pub async fn get_suggestion(&self, suggestion_id: Uuid, user_id: Uuid) -> Result<AiPricingSuggestion> {
+        sqlx::query_as::<_, AiPricingSuggestion>(
+            "SELECT * FROM ai_pricing_suggestions WHERE id = $1 AND user_id = $2"
+        )
+        .bind(suggestion_id)
+        .bind(user_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Pricing suggestion not found".to_string()))
+    }
+
+    /// User's suggestions, newest first, optionally for one NDC
+    pub async fn get_history(&self, user_id: Uuid, query: &PricingHistoryQuery) -> Result<Vec<AiPricingSuggestion>> {
+        let suggestions = sqlx::query_as::<_, AiPricingSuggestion>(
+            r#"
+            SELECT * FROM ai_pricing_suggestions
+            WHERE user_id = $1
+              AND ($2::text IS NULL OR REPLACE(ndc_code, '-', '') = REPLACE($2, '-', ''))
+            ORDER BY created_at DESC
+            LIMIT $3
+            "#
+        )
+        .bind(user_id)
+        .bind(query.ndc_code.as_deref())
+        .bind(query.limit.unwrap_or(50).clamp(1, 200))
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(suggestions)
+    }
+
+    /// Get user's quota status
+    pub async fn get_quota_status(&self, user_id: Uuid) -> Result<(i32, i32, i32)> {
+        let quota = sqlx::query_as::<_, (i32, i32)>(
+            r#"
+            SELECT monthly_pricing_suggestion_limit, monthly_pricing_suggestions_used
+            FROM user_ai_usage_limits
+            WHERE user_id = $1
+            "#
+        )
+        .bind(user_id)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        match quota {
+            Some((limit, used)) => Ok((limit, used, (limit - used).max(0))),
+            None => Ok((100, 0, 100)), // Default quota
+        }
+    }
+}
+
+/// Market data for the prompt; the snapshot is serialized as-is so the model sees exactly
+/// what is stored with the suggestion
+fn build_prompt(context: &PricingMarketContext, expiry_from: NaiveDate, expiry_to: NaiveDate) -> String {
+    let product = [
+        context.brand_name.as_deref(),
+        context.generic_name.as_deref(),
+        context.strength.as_deref(),
+        context.dosage_form.as_deref(),
+    ]
+    .iter()
+    .flatten()
+    .copied()
+    .collect::<Vec<_>>()
+    .join(" ");
+
+    format!(
+        r#"LISTING TO PRICE:
+
+Product: {} (NDC {})
+Manufacturer: {}
+Quantity: {} units
+Expiry window: {} to {} ({} days until the earliest expiry)
+
+MARKET DATA (JSON):
+{}
+
+Suggest a unit price for this listing."#,
+        product,
+        context.ndc_code,
+        context.manufacturer.as_deref().unwrap_or("Unknown"),
+        context.quantity,
+        expiry_from,
+        expiry_to,
+        context.days_to_expiry,
+        serde_json::to_string_pretty(context).unwrap_or_default()
+    )
+}
@@ -0,0 +1,483 @@
+/// Product Image Service
+///
+/// Stores, moderates and serves images attached to catalog pharmaceuticals.
+/// - Validates uploads by magic bytes (PNG, JPEG, WebP only)
+/// - Rejects oversized files and decompression bombs before decoding
+/// - Generates a bounded thumbnail alongside every original
+/// - Seller uploads start as `pending`; admin uploads are auto-approved
+
+use std::io::Cursor;
+use image::{ImageFormat, ImageReader};
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::{
+    middleware::error_handling::{AppError, Result},
+    models::product_image::*,
+    utils::file_storage::FileStorage,
+};
+
+/// Maximum accepted upload size (10 MB)
+pub const MAX_IMAGE_SIZE: usize = 10 * 1024 * 1024;
+
+/// Maximum accepted width/height in pixels
+pub const MAX_IMAGE_DIMENSION: u32 = 6000;
+
+/// Longest edge of generated thumbnails in pixels
+pub const THUMBNAIL_SIZE: u32 = 320;
+
+/// Maximum number of images per pharmaceutical
+pub const MAX_IMAGES_PER_PRODUCT: i64 = 12;
+
+/// Decoded image metadata and generated thumbnail
+pub struct ProcessedImage {
+    pub format: ImageFormat,
+    pub width: u32,
+    pub height: u32,
+    pub thumbnail: Vec<u8>,
+    pub thumbnail_format: ImageFormat,
+}
+
+pub struct ProductImageService {
+    db_pool: PgPool,
+    storage: FileStorage,
+}
+
+impl ProductImageService {
+    pub fn new(db_pool: PgPool, file_storage_path: &str) -> Result<Self> {
+        let storage = FileStorage::new(std::path::Path::new(file_storage_path).join("product_images"))?;
+        Ok(Self { db_pool, storage })
+    }
+
+    /// Upload a new image for a pharmaceutical
+    pub async fn upload_image(
+        &self,
+        pharmaceutical_id: Uuid,
+        uploaded_by: Uuid,
+        auto_approve: bool,
+        filename: &str,
+        data: &[u8],
+        alt_text: Option<String>,
+        is_primary: bool,
+    ) -> Result<PharmaceuticalImage> {
+        let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM pharmaceuticals WHERE id = $1)")
+            .bind(pharmaceutical_id)
+            .fetch_one(&self.db_pool)
+            .await?;
+
+        if !exists {
+            return Err(AppError::NotFound("Pharmaceutical not found".to_string()));
+        }
+
+        let image_count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM pharmaceutical_images WHERE pharmaceutical_id = $1 AND moderation_status != 'rejected'"
+        )
+        .bind(pharmaceutical_id)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        if image_count >= MAX_IMAGES_PER_PRODUCT {
+            return Err(AppError::BadRequest(format!(
+                "A pharmaceutical may have at most {} images",
+                MAX_IMAGES_PER_PRODUCT
+            )));
+        }
+
+        let processed = process_image(data)?;
+
+        let image_id = Uuid::new_v4();
+        let original_name = format!("original.{}", extension_for(processed.format));
+        let thumbnail_name = format!("thumbnail.{}", extension_for(processed.thumbnail_format));
+
+        let (file_path, file_hash) = self.storage.save_file(image_id, &original_name, data)?;
+        let (thumbnail_path, _) = self.storage.save_file(image_id, &thumbnail_name, &processed.thumbnail)?;
+
+        let status = if auto_approve {
+            ModerationStatus::Approved
+        } else {
+            ModerationStatus::Pending
+        };
+
+        let mut tx = self.db_pool.begin().await?;
+
+        // Only approved images can be primary; pending ones are promoted on approval
+        let make_primary = is_primary && auto_approve;
+        if make_primary {
+            sqlx::query("UPDATE pharmaceutical_images SET is_primary = FALSE, updated_at = NOW() WHERE pharmaceutical_id = $1 AND is_primary = TRUE")
+                .bind(pharmaceutical_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        let image = sqlx::query_as::<_, PharmaceuticalImage>(
+            r#"
+            INSERT INTO pharmaceutical_images (
+                id, pharmaceutical_id, uploaded_by, file_path, thumbnail_path,
+                original_filename, content_type, file_size_bytes, file_hash,
+                width, height, alt_text, is_primary, moderation_status,
+                moderated_by, moderated_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+            RETURNING *
+            "#
+        )
+        .bind(image_id)
+        .bind(pharmaceutical_id)
+        .bind(uploaded_by)
+        .bind(&file_path)
+        .bind(&thumbnail_path)
+        .bind(crate::utils::log_sanitizer::sanitize_for_log(filename))
+        .bind(content_type_for(processed.format))
+        .bind(data.len() as i64)
+        .bind(&file_hash)
+        .bind(processed.width as i32)
+        .bind(processed.height as i32)
+        .bind(alt_text)
+        .bind(make_primary)
+        .bind(status.as_str())
+        .bind(if auto_approve { Some(uploaded_by) } else { None })
+        .bind(if auto_approve { Some(chrono::Utc::now()) } else { None })
+        .fetch_one(&mut *tx)
+        .await;
+
+        let image = match image {
+            Ok(image) => image,
+            Err(e) => {
+                // Don't leave orphaned files behind if the insert fails
+                let _ = self.storage.delete_file(&thumbnail_path);
+                let _ = self.storage.delete_file(&file_path);
+                return Err(e.into());
+            }
+        };
+
+        tx.commit().await?;
+
+        tracing::info!(
+            "Product image {} uploaded for pharmaceutical {} by {} (status: {})",
+            image.id,
+            pharmaceutical_id,
+            uploaded_by,
+            image.moderation_status
+        );
+
+        Ok(image)
+    }
+
+    /// List images for a pharmaceutical.
+    ///
+    /// Approved images are always returned. Unmoderated images are included only
+    /// for admins, or for the viewer's own uploads.
+    pub async fn list_images(
+        &self,
+        pharmaceutical_id: Uuid,
+        viewer_id: Option<Uuid>,
+        viewer_is_admin: bool,
+        include_unmoderated: bool,
+    ) -> Result<Vec<PharmaceuticalImage>> {
+        let images = sqlx::query_as::<_, PharmaceuticalImage>(
+            r#"
+            SELECT * FROM pharmaceutical_images
+            WHERE pharmaceutical_id = $1
+              AND (
+                moderation_status = 'approved'
+                OR ($2 AND ($3 OR uploaded_by = $4))
+              )
+            ORDER BY is_primary DESC, created_at ASC
+            "#
+        )
+        .bind(pharmaceutical_id)
+        .bind(include_unmoderated)
+        .bind(viewer_is_admin)
+        .bind(viewer_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(images)
+    }
+
+    pub async fn get_image(&self, pharmaceutical_id: Uuid, image_id: Uuid) -> Result<PharmaceuticalImage> {
+        sqlx::query_as::<_, PharmaceuticalImage>(
+            "SELECT * FROM pharmaceutical_images WHERE id = $1 AND pharmaceutical_id = $2"
+        )
+        .bind(image_id)
+        .bind(pharmaceutical_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Image not found".to_string()))
+    }
+
+    /// Read the original or thumbnail bytes from storage
+    pub fn read_image_file(&self, image: &PharmaceuticalImage, thumbnail: bool) -> Result<Vec<u8>> {
+        if thumbnail {
+            self.storage.read_file(&image.thumbnail_path)
+        } else {
+            self.storage.read_file(&image.file_path)
+        }
+    }
+
+    /// Approve or reject an image (admin only)
+    pub async fn moderate_image(
+        &self,
+        pharmaceutical_id: Uuid,
+        image_id: Uuid,
+        moderator_id: Uuid,
+        request: ModerateImageRequest,
+    ) -> Result<PharmaceuticalImage> {
+        if request.status == ModerationStatus::Pending {
+            return Err(AppError::BadRequest("Moderation status must be approved or rejected".to_string()));
+        }
+
+        if request.status == ModerationStatus::Rejected
+            && request.rejection_reason.as_deref().map(str::trim).unwrap_or("").is_empty()
+        {
+            return Err(AppError::BadRequest("A rejection reason is required".to_string()));
+        }
+
+        let mut tx = self.db_pool.begin().await?;
+
+        let image = sqlx::query_as::<_, PharmaceuticalImage>(
+            r#"
+            UPDATE pharmaceutical_images
+            SET moderation_status = $3,
+                rejection_reason = $4,
+                moderated_by = $5,
+                moderated_at = NOW(),
+                is_primary = CASE WHEN $3 = 'rejected' THEN FALSE ELSE is_primary END,
+                updated_at = NOW()
+            WHERE id = $1 AND pharmaceutical_id = $2
+            RETURNING *
+            "#
+        )
+        .bind(image_id)
+        .bind(pharmaceutical_id)
+        .bind(request.status.as_str())
+        .bind(if request.status == ModerationStatus::Rejected { request.rejection_reason } else { None })
+        .bind(moderator_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Image not found".to_string()))?;
+
+        // First approved image becomes the primary image
+        let image = if request.status == ModerationStatus::Approved {
+            let has_primary: bool = sqlx::query_scalar(
+                "SELECT EXISTS(SELECT 1 FROM pharmaceutical_images WHERE pharmaceutical_id = $1 AND is_primary = TRUE)"
+            )
+            .bind(pharmaceutical_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            if has_primary {
+                image
+            } else {
+                sqlx::query_as::<_, PharmaceuticalImage>(
+                    "UPDATE pharmaceutical_images SET is_primary = TRUE, updated_at = NOW() WHERE id = $1 RETURNING *"
+                )
+                .bind(image_id)
+                .fetch_one(&mut *tx)
+                .await?
+            }
+        } else {
+            image
+        };
+
+        tx.commit().await?;
+
+        tracing::info!(
+            "Product image {} moderated by {}: {}",
+            image_id,
+            moderator_id,
+            image.moderation_status
+        );
+
+        Ok(image)
+    }
+
+    /// Make an approved image the primary image for its pharmaceutical
+    pub async fn set_primary(&self, pharmaceutical_id: Uuid, image_id: Uuid) -> Result<PharmaceuticalImage> {
+        let image = self.get_image(pharmaceutical_id, image_id).await?;
+        if image.moderation_status != ModerationStatus::Approved.as_str() {
+            return Err(AppError::BadRequest("Only approved images can be primary".to_string()));
+        }
+
+        let mut tx = self.db_pool.begin().await?;
+
+        sqlx::query("UPDATE pharmaceutical_images SET is_primary = FALSE, updated_at = NOW() WHERE pharmaceutical_id = $1 AND is_primary = TRUE")
+            .bind(pharmaceutical_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let image = sqlx::query_as::<_, PharmaceuticalImage>(
+            "UPDATE pharmaceutical_images SET is_primary = TRUE, updated_at = NOW() WHERE id = $1 RETURNING *"
+        )
+        .bind(image_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(image)
+    }
+
+    /// Delete an image and its files. Admins may delete any image, sellers only their own.
+    pub async fn delete_image(
+        &self,
+        pharmaceutical_id: Uuid,
+        image_id: Uuid,
+        user_id: Uuid,
+        is_admin: bool,
+    ) -> Result<()> {
+        let image = self.get_image(pharmaceutical_id, image_id).await?;
+
+        if !is_admin && image.uploaded_by != Some(user_id) {
+            return Err(AppError::Forbidden("You can only delete your own images".to_string()));
+        }
+
+        sqlx::query("DELETE FROM pharmaceutical_images WHERE id = $1")
+            .bind(image_id)
+            .execute(&self.db_pool)
+            .await?;
+
+        self.storage.delete_file(&image.thumbnail_path)?;
+        self.storage.delete_file(&image.file_path)?;
+
+        tracing::info!("Product image {} deleted by {}", image_id, user_id);
+
+        Ok(())
+    }
+
+    /// Images awaiting moderation, oldest first
+    pub async fn get_moderation_queue(&self, limit: i64, offset: i64) -> Result<Vec<PharmaceuticalImage>> {
+        let images = sqlx::query_as::<_, PharmaceuticalImage>(
+            r#"
+            SELECT * FROM pharmaceutical_images
+            WHERE moderation_status = 'pending'
+            ORDER BY created_at ASC
+            LIMIT $1 OFFSET $2
+            "#
+        )
+        .bind(limit.clamp(1, 100))
+        .bind(offset.max(0))
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(images)
+    }
+}
+
+/// Validate an uploaded image and generate its thumbnail
+pub fn process_image(data: &[u8]) -> Result<ProcessedImage> {
+    if data.is_empty() {
+        return Err(AppError::InvalidInput("Image file is empty".to_string()));
+    }
+
+    if data.len() > MAX_IMAGE_SIZE {
+        return Err(AppError::InvalidInput(format!(
+            "Image too large. Maximum size is {}MB",
+            MAX_IMAGE_SIZE / 1024 / 1024
+        )));
+    }
+
+    // 🔒 SECURITY: Detect format from magic bytes, never from filename or client Content-Type
+    let format = image::guess_format(data)
+        .map_err(|_| AppError::InvalidInput("Unrecognized image format".to_string()))?;
+
+    if !matches!(format, ImageFormat::Png | ImageFormat::Jpeg | ImageFormat::WebP) {
+        return Err(AppError::InvalidInput(
+            "Unsupported image format. Allowed: PNG, JPEG, WebP".to_string(),
+        ));
+    }
+
+    // 🔒 SECURITY: Read dimensions from the header before decoding to reject decompression bombs
+    let (width, height) = ImageReader::with_format(Cursor::new(data), format)
+        .into_dimensions()
+        .map_err(|_| AppError::InvalidInput("Corrupt image file".to_string()))?;
+
+    if width == 0 || height == 0 || width > MAX_IMAGE_DIMENSION || height > MAX_IMAGE_DIMENSION {
+        return Err(AppError::InvalidInput(format!(
+            "Image dimensions must be between 1 and {} pixels",
+            MAX_IMAGE_DIMENSION
+        )));
+    }
+
+    let decoded = image::load_from_memory_with_format(data, format)
+        .map_err(|_| AppError::InvalidInput("Corrupt image file".to_string()))?;
+
+    // JPEG has no alpha channel, so keep photos as JPEG and everything else as PNG
+    let thumbnail_format = if format == ImageFormat::Jpeg {
+        ImageFormat::Jpeg
+    } else {
+        ImageFormat::Png
+    };
+
+    let mut thumbnail = Cursor::new(Vec::new());
+    decoded
+        .thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+        .write_to(&mut thumbnail, thumbnail_format)
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to encode thumbnail: {}", e)))?;
+
+    Ok(ProcessedImage {
+        format,
+        width,
+        height,
+        thumbnail: thumbnail.into_inner(),
+        thumbnail_format,
+    })
+}
+
+pub fn content_type_for(format: ImageFormat) -> &'static str {
+    match format {
+        ImageFormat::Png => "image/png",
+        ImageFormat::Jpeg => "image/jpeg",
+        ImageFormat::WebP => "image/webp",
+        _ => "application/octet-stream",
+    }
+}
+
+fn extension_for(format: ImageFormat) -> &'static str {
+    match format {
+        ImageFormat::Png => "png",
+        ImageFormat::Jpeg => "jpg",
+        ImageFormat::WebP => "webp",
+        _ => "bin",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(width: u32, height: u32, format: ImageFormat) -> Vec<u8> {
+        let img = image::DynamicImage::new_rgb8(width, height);
+        let mut buf = Cursor::new(Vec::new());
+        img.write_to(&mut buf, format).unwrap();
+        buf.into_inner()
+    }
+
+    #[test]
+    fn test_process_png_generates_bounded_thumbnail() {
+        let processed = process_image(&encode(1000, 500, ImageFormat::Png)).unwrap();
+        assert_eq!(processed.format, ImageFormat::Png);
+        assert_eq!((processed.width, processed.height), (1000, 500));
+
+        let thumb = image::load_from_memory(&processed.thumbnail).unwrap();
+        assert!(thumb.width() <= THUMBNAIL_SIZE && thumb.height() <= THUMBNAIL_SIZE);
+    }
+
+    #[test]
+    fn test_jpeg_thumbnail_stays_jpeg() {
+        let processed = process_image(&encode(400, 400, ImageFormat::Jpeg)).unwrap();
+        assert_eq!(processed.thumbnail_format, ImageFormat::Jpeg);
+        assert_eq!(content_type_for(processed.format), "image/jpeg");
+    }
+
+    #[test]
+    fn test_rejects_non_image_data() {
+        assert!(process_image(b"%PDF-1.4 not an image").is_err());
+        assert!(process_image(&[]).is_err());
+    }
+
+    #[test]
+    fn test_rejects_oversized_dimensions() {
+        let data = encode(MAX_IMAGE_DIMENSION + 1, 1, ImageFormat::Png);
+        assert!(process_image(&data).is_err());
+    }
+}
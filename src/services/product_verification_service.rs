@@ -0,0 +1,265 @@
+/// Product Verification Service
+///
+/// VRS-style verification of DSCSA product identifiers against serialized inventory,
+/// e.g. for saleable returns. A lookup is answered from the registered serial, its lot
+/// and the OpenFDA recalls of the product, and is logged with its outcome and response
+/// time (VERIFICATION_SLA_MS, default 1000 ms).
+
+use crate::{
+    middleware::error_handling::{AppError, Result},
+    models::openfda_recall::extract_ndcs,
+    models::product_verification::*,
+};
+use chrono::Utc;
+use sqlx::PgPool;
+use std::time::Instant;
+use uuid::Uuid;
+
+pub struct ProductVerificationService {
+    db_pool: PgPool,
+    sla_ms: i32,
+}
+
+impl ProductVerificationService {
+    pub fn new(db_pool: PgPool) -> Self {
+        let sla_ms = std::env::var("VERIFICATION_SLA_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|ms: &i32| *ms > 0)
+            .unwrap_or(DEFAULT_VERIFICATION_SLA_MS);
+
+        Self { db_pool, sla_ms }
+    }
+
+    /// Verify a product identifier and log the lookup
+    pub async fn lookup(
+        &self,
+        requester_id: Uuid,
+        request: VerificationLookupRequest,
+    ) -> Result<VerificationLookupResponse> {
+        let started = Instant::now();
+        let gtin = normalize_gtin(&request.gtin).map_err(AppError::BadRequest)?;
+        let serial_number = request.serial_number.trim().to_string();
+
+        let unit = sqlx::query_as::<_, SerializedUnit>(
+            r#"
+            SELECT s.id AS serial_id, s.status, i.batch_number, i.expiry_date, p.ndc_code
+            FROM inventory_serial_numbers s
+            JOIN inventory i ON i.id = s.inventory_id
+            JOIN pharmaceuticals p ON p.id = i.pharmaceutical_id
+            WHERE s.gtin = $1 AND s.serial_number = $2
+            "#,
+        )
+        .bind(&gtin)
+        .bind(&serial_number)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        let recall_number = match &unit {
+            Some(unit) => self.active_recall(unit).await?,
+            None => None,
+        };
+
+        let (status, additional_info) = evaluate_verification(
+            &request,
+            unit.as_ref(),
+            recall_number.is_some(),
+            Utc::now().date_naive(),
+        );
+
+        let response_time_ms = started.elapsed().as_millis().min(i32::MAX as u128) as i32;
+        let within_sla = response_time_ms <= self.sla_ms;
+        if !within_sla {
+            tracing::warn!(
+                "Product verification took {} ms (SLA {} ms) for GTIN {}",
+                response_time_ms,
+                self.sla_ms,
+                gtin
+            );
+        }
+
+        let verification_id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO product_verification_requests (
+                requester_id, correlation_id, gtin, serial_number, lot_number, expiry_date,
+                status, additional_info, serial_id, recall_number, response_time_ms, within_sla
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            RETURNING id
+            "#,
+        )
+        .bind(requester_id)
+        .bind(&request.correlation_id)
+        .bind(&gtin)
+        .bind(&serial_number)
+        .bind(request.lot_number.trim())
+        .bind(request.expiry_date)
+        .bind(status.as_str())
+        .bind(additional_info.map(|d| d.as_str()))
+        .bind(unit.as_ref().map(|u| u.serial_id))
+        .bind(&recall_number)
+        .bind(response_time_ms)
+        .bind(within_sla)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        tracing::info!(
+            "Audit: User {} verified GTIN {} serial {}: {} ({:?}, {} ms)",
+            requester_id,
+            gtin,
+            serial_number,
+            status.as_str(),
+            additional_info,
+            response_time_ms
+        );
+
+        Ok(VerificationLookupResponse {
+            verification_id,
+            correlation_id: request.correlation_id,
+            gtin,
+            serial_number,
+            status,
+            verified: status == VerificationStatus::Verified,
+            additional_info,
+            recall_number,
+            response_time_ms,
+            within_sla,
+            responded_at: Utc::now(),
+        })
+    }
+
+    /// Register serial numbers for one of the user's inventory lots. The GTIN must encode
+    /// the lot's NDC.
+    pub async fn register_serials(
+        &self,
+        user_id: Uuid,
+        request: RegisterSerialNumbersRequest,
+    ) -> Result<RegisterSerialNumbersResponse> {
+        if request.serial_numbers.len() > MAX_SERIALS_PER_REQUEST {
+            return Err(AppError::BadRequest(format!(
+                "At most {} serial numbers per request",
+                MAX_SERIALS_PER_REQUEST
+            )));
+        }
+        let gtin = normalize_gtin(&request.gtin).map_err(AppError::BadRequest)?;
+
+        let lot: Option<(Uuid, Option<String>)> = sqlx::query_as(
+            r#"
+            SELECT i.user_id, p.ndc_code
+            FROM inventory i
+            JOIN pharmaceuticals p ON p.id = i.pharmaceutical_id
+            WHERE i.id = $1
+            "#,
+        )
+        .bind(request.inventory_id)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        let ndc_code = match lot {
+            None => return Err(AppError::NotFound("Inventory item not found".to_string())),
+            Some((owner_id, _)) if owner_id != user_id => {
+                return Err(AppError::Forbidden("Access denied".to_string()));
+            }
+            Some((_, ndc_code)) => ndc_code,
+        };
+        if let Some(ndc) = &ndc_code {
+            if !gtin_matches_ndc(&gtin, ndc) {
+                return Err(AppError::BadRequest(format!(
+                    "GTIN {} does not encode the product NDC {}",
+                    gtin, ndc
+                )));
+            }
+        }
+
+        let mut serials: Vec<String> = Vec::with_capacity(request.serial_numbers.len());
+        for serial in &request.serial_numbers {
+            let serial = serial.trim();
+            if serial.is_empty() || serial.len() > 20 {
+                return Err(AppError::BadRequest(format!(
+                    "Invalid serial number '{}': must be 1-20 characters",
+                    serial
+                )));
+            }
+            if !serials.iter().any(|s| s == serial) {
+                serials.push(serial.to_string());
+            }
+        }
+
+        let inserted: Vec<String> = sqlx::query_scalar(
+            r#"
+            INSERT INTO inventory_serial_numbers (inventory_id, user_id, gtin, serial_number)
+            SELECT $1, $2, $3, UNNEST($4::TEXT[])
+            ON CONFLICT (gtin, serial_number) DO NOTHING
+            RETURNING serial_number
+            "#,
+        )
+        .bind(request.inventory_id)
+        .bind(user_id)
+        .bind(&gtin)
+        .bind(&serials)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let duplicates: Vec<String> = serials.into_iter().filter(|s| !inserted.contains(s)).collect();
+
+        tracing::info!(
+            "Registered {} serial numbers (GTIN {}) for inventory {}; {} already registered",
+            inserted.len(),
+            gtin,
+            request.inventory_id,
+            duplicates.len()
+        );
+
+        Ok(RegisterSerialNumbersResponse { registered: inserted.len(), duplicates })
+    }
+
+    /// The user's lookups, newest first
+    pub async fn history(&self, requester_id: Uuid, limit: i64, offset: i64) -> Result<Vec<ProductVerificationRecord>> {
+        let records = sqlx::query_as::<_, ProductVerificationRecord>(
+            r#"
+            SELECT id, correlation_id, gtin, serial_number, lot_number, expiry_date, status,
+                   additional_info, recall_number, response_time_ms, within_sla, created_at
+            FROM product_verification_requests
+            WHERE requester_id = $1
+            ORDER BY created_at DESC
+            LIMIT $2 OFFSET $3
+            "#,
+        )
+        .bind(requester_id)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(records)
+    }
+
+    /// Open recall of the unit's product that names its lot
+    async fn active_recall(&self, unit: &SerializedUnit) -> Result<Option<String>> {
+        let Some(product_ndcs) = unit.ndc_code.as_deref().map(|ndc| {
+            extract_ndcs(ndc).into_iter().map(|(product_ndc, _)| product_ndc).collect::<Vec<_>>()
+        }) else {
+            return Ok(None);
+        };
+        if product_ndcs.is_empty() {
+            return Ok(None);
+        }
+
+        let recall_number: Option<String> = sqlx::query_scalar(
+            r#"
+            SELECT recall_number FROM openfda_recalls
+            WHERE product_ndcs && $1
+              AND COALESCE(status, '') NOT IN ('Completed', 'Terminated')
+              AND POSITION(UPPER($2) IN UPPER(COALESCE(code_info, ''))) > 0
+            ORDER BY report_date DESC NULLS LAST
+            LIMIT 1
+            "#,
+        )
+        .bind(&product_ndcs)
+        .bind(unit.batch_number.trim())
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        Ok(recall_number)
+    }
+}
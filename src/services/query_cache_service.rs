@@ -0,0 +1,383 @@
+/// Query cache - read-through in-memory cache for hot catalog and search queries
+///
+/// Cached queries:
+/// - OpenFDA lookups by NDC and EMA lookups by EU number
+/// - marketplace searches (short TTL, flushed on inventory writes)
+///
+/// Catalog entries live until the next sync of their source completes: syncs running
+/// in this instance flush them directly, and `QueryCacheSyncWatcher` flushes them when
+/// another instance completes a sync. TTLs only bound how long an entry can outlive a
+/// missed event. Each instance has its own cache, so nothing is shared between
+/// replicas beyond the sync watcher.
+///
+/// Hits and misses are exported as `atlas_query_cache_lookups_total`.
+///
+/// Configuration:
+/// - QUERY_CACHE_ENABLED (default true)
+/// - QUERY_CACHE_DISABLED: comma-separated cache names to bypass
+/// - QUERY_CACHE_MARKETPLACE_TTL_SECONDS (default 30, 0 disables marketplace caching)
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use sqlx::PgPool;
+use std::any::Any;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use crate::middleware::{error_handling::Result, metrics};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QueryCacheKind {
+    OpenFdaNdc,
+    EmaEuNumber,
+    MarketplaceSearch,
+}
+
+impl QueryCacheKind {
+    pub const ALL: [QueryCacheKind; 3] = [
+        QueryCacheKind::OpenFdaNdc,
+        QueryCacheKind::EmaEuNumber,
+        QueryCacheKind::MarketplaceSearch,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QueryCacheKind::OpenFdaNdc => "openfda_ndc",
+            QueryCacheKind::EmaEuNumber => "ema_eu_number",
+            QueryCacheKind::MarketplaceSearch => "marketplace_search",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.as_str() == name)
+    }
+
+    /// The catalog whose sync invalidates this cache
+    pub fn source(&self) -> Option<CatalogSource> {
+        match self {
+            QueryCacheKind::OpenFdaNdc => Some(CatalogSource::OpenFda),
+            QueryCacheKind::EmaEuNumber => Some(CatalogSource::Ema),
+            _ => None,
+        }
+    }
+
+    /// Catalog entries are flushed by sync events, so their TTL is a safety net
+    fn default_ttl(&self) -> Duration {
+        match self {
+            QueryCacheKind::OpenFdaNdc | QueryCacheKind::EmaEuNumber => Duration::from_secs(24 * 3600),
+            QueryCacheKind::MarketplaceSearch => Duration::from_secs(30),
+        }
+    }
+
+    fn max_entries(&self) -> usize {
+        match self {
+            QueryCacheKind::OpenFdaNdc => 20_000,
+            QueryCacheKind::EmaEuNumber => 5_000,
+            QueryCacheKind::MarketplaceSearch => 2_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CatalogSource {
+    OpenFda,
+    Ema,
+}
+
+/// Cache settings from the environment
+#[derive(Debug, Clone)]
+pub struct QueryCacheConfig {
+    pub enabled: bool,
+    pub disabled: Vec<String>,
+    pub marketplace_ttl: Duration,
+}
+
+impl QueryCacheConfig {
+    pub fn from_env() -> Self {
+        Self {
+            enabled: std::env::var("QUERY_CACHE_ENABLED")
+                .map(|v| v != "false" && v != "0")
+                .unwrap_or(true),
+            disabled: std::env::var("QUERY_CACHE_DISABLED")
+                .unwrap_or_default()
+                .split(',')
+                .map(|c| c.trim().to_lowercase())
+                .filter(|c| !c.is_empty())
+                .collect(),
+            marketplace_ttl: std::env::var("QUERY_CACHE_MARKETPLACE_TTL_SECONDS")
+                .ok()
+                .and_then(|s| s.parse::<u64>().ok())
+                .map(Duration::from_secs)
+                .unwrap_or_else(|| QueryCacheKind::MarketplaceSearch.default_ttl()),
+        }
+    }
+
+    pub fn ttl(&self, kind: QueryCacheKind) -> Duration {
+        match kind {
+            QueryCacheKind::MarketplaceSearch => self.marketplace_ttl,
+            _ => kind.default_ttl(),
+        }
+    }
+
+    pub fn is_enabled(&self, kind: QueryCacheKind) -> bool {
+        self.enabled && !self.ttl(kind).is_zero() && !self.disabled.iter().any(|c| c == kind.as_str())
+    }
+}
+
+static CONFIG: Lazy<QueryCacheConfig> = Lazy::new(QueryCacheConfig::from_env);
+
+struct CacheEntry {
+    value: Arc<dyn Any + Send + Sync>,
+    expires_at: Instant,
+}
+
+#[derive(Default)]
+struct CacheShard {
+    entries: HashMap<String, CacheEntry>,
+    /// Bumped on every invalidation so loads started before it aren't stored
+    generation: u64,
+    hits: u64,
+    misses: u64,
+    last_invalidated_at: Option<DateTime<Utc>>,
+}
+
+impl CacheShard {
+    /// Make room for one more entry: drop expired entries, then the one expiring first
+    fn evict(&mut self, max_entries: usize, now: Instant) {
+        if self.entries.len() < max_entries {
+            return;
+        }
+        self.entries.retain(|_, entry| entry.expires_at > now);
+        if self.entries.len() >= max_entries {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.expires_at)
+                .map(|(key, _)| key.clone());
+            if let Some(key) = oldest {
+                self.entries.remove(&key);
+            }
+        }
+    }
+}
+
+static CACHES: Lazy<DashMap<QueryCacheKind, CacheShard>> = Lazy::new(DashMap::new);
+
+/// Usage of one cache in this instance since startup
+#[derive(Debug, Clone, Serialize)]
+pub struct QueryCacheStats {
+    pub cache: &'static str,
+    pub enabled: bool,
+    pub entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub hit_rate: Option<f64>,
+    pub ttl_seconds: u64,
+    pub last_invalidated_at: Option<DateTime<Utc>>,
+}
+
+pub struct QueryCache;
+
+impl QueryCache {
+    pub fn config() -> &'static QueryCacheConfig {
+        &CONFIG
+    }
+
+    /// The cached value for `key`, or the result of `load` (stored on success)
+    pub async fn get_or_load<T, F, Fut>(kind: QueryCacheKind, key: &str, load: F) -> Result<T>
+    where
+        T: Clone + Send + Sync + 'static,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        if !CONFIG.is_enabled(kind) {
+            return load().await;
+        }
+
+        let generation = {
+            let mut shard = CACHES.entry(kind).or_default();
+            let cached = shard
+                .entries
+                .get(key)
+                .filter(|entry| entry.expires_at > Instant::now())
+                .and_then(|entry| entry.value.downcast_ref::<T>().cloned());
+            metrics::record_query_cache_lookup(kind.as_str(), cached.is_some());
+            match cached {
+                Some(value) => {
+                    shard.hits += 1;
+                    return Ok(value);
+                }
+                None => {
+                    shard.misses += 1;
+                    shard.generation
+                }
+            }
+        };
+
+        let value = load().await?;
+
+        let mut shard = CACHES.entry(kind).or_default();
+        if shard.generation == generation {
+            let now = Instant::now();
+            shard.evict(kind.max_entries(), now);
+            shard.entries.insert(key.to_string(), CacheEntry {
+                value: Arc::new(value.clone()),
+                expires_at: now + CONFIG.ttl(kind),
+            });
+            metrics::record_query_cache_entries(kind.as_str(), shard.entries.len());
+        }
+
+        Ok(value)
+    }
+
+    /// Drop every entry of a cache. `reason` labels the invalidation metric.
+    pub fn invalidate(kind: QueryCacheKind, reason: &str) {
+        let mut shard = CACHES.entry(kind).or_default();
+        shard.entries.clear();
+        shard.generation += 1;
+        shard.last_invalidated_at = Some(Utc::now());
+        metrics::record_query_cache_invalidation(kind.as_str(), reason, 0);
+    }
+
+    /// Drop the caches built from a catalog after one of its syncs completed
+    pub fn invalidate_source(source: CatalogSource) {
+        for kind in QueryCacheKind::ALL.into_iter().filter(|kind| kind.source() == Some(source)) {
+            Self::invalidate(kind, "sync_completed");
+        }
+    }
+
+    pub fn stats() -> Vec<QueryCacheStats> {
+        QueryCacheKind::ALL
+            .into_iter()
+            .map(|kind| {
+                let (entries, hits, misses, last_invalidated_at) = CACHES
+                    .get(&kind)
+                    .map(|shard| (shard.entries.len(), shard.hits, shard.misses, shard.last_invalidated_at))
+                    .unwrap_or_default();
+                let lookups = hits + misses;
+                QueryCacheStats {
+                    cache: kind.as_str(),
+                    enabled: CONFIG.is_enabled(kind),
+                    entries,
+                    hits,
+                    misses,
+                    hit_rate: (lookups > 0).then(|| hits as f64 / lookups as f64),
+                    ttl_seconds: CONFIG.ttl(kind).as_secs(),
+                    last_invalidated_at,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Completion times of the latest OpenFDA and EMA syncs
+type LastCompletedSyncs = (Option<DateTime<Utc>>, Option<DateTime<Utc>>);
+
+/// Flushes catalog caches when an OpenFDA or EMA sync completes in any instance
+pub struct QueryCacheSyncWatcher {
+    db_pool: PgPool,
+    interval: Duration,
+}
+
+impl QueryCacheSyncWatcher {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self {
+            db_pool,
+            interval: Duration::from_secs(60),
+        }
+    }
+
+    pub async fn run(&self) {
+        let mut interval = tokio::time::interval(self.interval);
+        let mut last_seen: Option<LastCompletedSyncs> = None;
+
+        loop {
+            interval.tick().await;
+
+            let completed = match self.last_completed_syncs().await {
+                Ok(completed) => completed,
+                Err(e) => {
+                    tracing::warn!("Query cache sync watcher could not read sync logs: {:?}", e);
+                    continue;
+                }
+            };
+
+            // The first check only records where the syncs stand
+            if let Some((openfda, ema)) = last_seen {
+                if completed.0 != openfda {
+                    QueryCache::invalidate_source(CatalogSource::OpenFda);
+                }
+                if completed.1 != ema {
+                    QueryCache::invalidate_source(CatalogSource::Ema);
+                }
+            }
+            last_seen = Some(completed);
+        }
+    }
+
+    /// Completion time of the latest OpenFDA drug catalog and EMA syncs. Suspect runs
+    /// count: their records are in the catalog even though they aren't a trusted refresh.
+    async fn last_completed_syncs(&self) -> Result<LastCompletedSyncs> {
+        let row: LastCompletedSyncs = sqlx::query_as(
+            r#"
+            SELECT
+                (SELECT MAX(sync_completed_at) FROM openfda_sync_log
+                 WHERE status IN ('completed', 'suspect')
+                   AND COALESCE(sync_type, 'full') NOT IN ('device', 'recall')),
+                (SELECT MAX(sync_completed_at) FROM ema_sync_log
+                 WHERE status IN ('completed', 'suspect'))
+            "#
+        )
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        Ok(row)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_read_through_and_invalidation() {
+        let kind = QueryCacheKind::EmaEuNumber;
+        let load = |value: &'static str| move || async move { Ok(Some(value.to_string())) };
+
+        assert_eq!(QueryCache::get_or_load(kind, "EU/1/00/001/001", load("first")).await.unwrap(), Some("first".to_string()));
+        // Served from the cache
+        assert_eq!(QueryCache::get_or_load(kind, "EU/1/00/001/001", load("second")).await.unwrap(), Some("first".to_string()));
+
+        QueryCache::invalidate_source(CatalogSource::Ema);
+        assert_eq!(QueryCache::get_or_load(kind, "EU/1/00/001/001", load("third")).await.unwrap(), Some("third".to_string()));
+
+        // Failed loads are not cached
+        let failed: Result<Option<String>> = QueryCache::get_or_load(kind, "EU/1/00/002/001", || async {
+            Err(crate::middleware::error_handling::AppError::NotFound("missing".to_string()))
+        }).await;
+        assert!(failed.is_err());
+        assert_eq!(QueryCache::get_or_load(kind, "EU/1/00/002/001", load("found")).await.unwrap(), Some("found".to_string()));
+    }
+
+    #[test]
+    fn test_eviction_keeps_cache_bounded() {
+        let mut shard = CacheShard::default();
+        let now = Instant::now();
+        for (i, ttl) in [(0, 30), (1, 10), (2, 20)] {
+            shard.entries.insert(i.to_string(), CacheEntry {
+                value: Arc::new(i),
+                expires_at: now + Duration::from_secs(ttl),
+            });
+        }
+
+        shard.evict(3, now);
+        assert_eq!(shard.entries.len(), 2);
+        assert!(!shard.entries.contains_key("1"));
+
+        shard.evict(3, now);
+        assert_eq!(shard.entries.len(), 2);
+    }
+}
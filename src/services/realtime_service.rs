@@ -0,0 +1,205 @@
+/// Realtime Service
+///
+/// Per-instance hub behind `GET /api/alerts/stream`. `run_listener` (started in main)
+/// LISTENs on the Postgres channel the notification / inquiry message triggers publish
+/// on, loads each announced row once and broadcasts it; every open stream filters the
+/// broadcast for its user. Because the announcement goes through Postgres, a row written
+/// by any API instance reaches streams held open by every other instance.
+
+use sqlx::{postgres::PgListener, PgPool};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+use uuid::Uuid;
+use crate::{
+    middleware::error_handling::{AppError, Result},
+    models::{alerts::*, inquiry_message::{InquiryMessage, InquiryMessageResponse}, realtime::*},
+};
+
+#[derive(sqlx::FromRow)]
+struct MessageRow {
+    id: Uuid,
+    inquiry_id: Uuid,
+    sender_id: Uuid,
+    message: String,
+    created_at: chrono::DateTime<chrono::Utc>,
+    company_name: String,
+}
+
+#[derive(Clone)]
+pub struct RealtimeHub {
+    sender: broadcast::Sender<Arc<RealtimeEvent>>,
+    open_streams: Arc<Mutex<HashMap<Uuid, usize>>>,
+}
+
+/// A user's open stream; the slot is released on drop (client disconnect)
+pub struct RealtimeSubscription {
+    pub receiver: broadcast::Receiver<Arc<RealtimeEvent>>,
+    user_id: Uuid,
+    open_streams: Arc<Mutex<HashMap<Uuid, usize>>>,
+}
+
+impl Drop for RealtimeSubscription {
+    fn drop(&mut self) {
+        let mut open = self.open_streams.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(count) = open.get_mut(&self.user_id) {
+            *count -= 1;
+            if *count == 0 {
+                open.remove(&self.user_id);
+            }
+        }
+    }
+}
+
+impl Default for RealtimeHub {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RealtimeHub {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(REALTIME_BUFFER_SIZE);
+        Self {
+            sender,
+            open_streams: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn subscribe(&self, user_id: Uuid) -> Result<RealtimeSubscription> {
+        let mut open = self.open_streams.lock().unwrap_or_else(|e| e.into_inner());
+        let count = open.entry(user_id).or_insert(0);
+        if *count >= MAX_STREAMS_PER_USER {
+            return Err(AppError::BadRequest(format!(
+                "At most {} notification streams can be open at once",
+                MAX_STREAMS_PER_USER
+            )));
+        }
+        *count += 1;
+
+        Ok(RealtimeSubscription {
+            receiver: self.sender.subscribe(),
+            user_id,
+            open_streams: self.open_streams.clone(),
+        })
+    }
+
+    /// Streams open on this instance
+    pub fn open_stream_count(&self) -> usize {
+        self.open_streams.lock().unwrap_or_else(|e| e.into_inner()).values().sum()
+    }
+
+    /// LISTEN for trigger announcements and fan them out. Runs for the life of the
+    /// process; the listener reconnects by itself after a lost connection.
+    pub async fn run_listener(self, db_pool: PgPool) {
+        let mut listener = loop {
+            match PgListener::connect_with(&db_pool).await {
+                Ok(mut listener) => match listener.listen(REALTIME_CHANNEL).await {
+                    Ok(()) => break listener,
+                    Err(e) => tracing::error!("❌ Realtime LISTEN failed: {}", e),
+                },
+                Err(e) => tracing::error!("❌ Realtime listener connection failed: {}", e),
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+        };
+
+        loop {
+            let notification = match listener.recv().await {
+                Ok(notification) => notification,
+                Err(e) => {
+                    // Notifications sent while disconnected are lost; streams resync on reconnect
+                    tracing::warn!("Realtime listener connection lost, reconnecting: {}", e);
+                    tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+                    continue;
+                }
+            };
+
+            // Nobody is streaming from this instance: skip loading the row
+            if self.sender.receiver_count() == 0 {
+                continue;
+            }
+
+            let notice: RealtimeNotice = match serde_json::from_str(notification.payload()) {
+                Ok(notice) => notice,
+                Err(e) => {
+                    tracing::warn!("Ignoring malformed realtime notice: {}", e);
+                    continue;
+                }
+            };
+
+            match load_event(&db_pool, notice).await {
+                Ok(Some(event)) => {
+                    let _ = self.sender.send(Arc::new(event));
+                }
+                Ok(None) => {}
+                Err(e) => tracing::warn!("Failed to load realtime event: {}", e),
+            }
+        }
+    }
+}
+
+/// Load the announced row in the shape the REST endpoints return it in
+async fn load_event(db_pool: &PgPool, notice: RealtimeNotice) -> Result<Option<RealtimeEvent>> {
+    let data = match notice.kind {
+        RealtimeEventKind::Notification => {
+            sqlx::query_as::<_, AlertNotification>("SELECT * FROM alert_notifications WHERE id = $1")
+                .bind(notice.id)
+                .fetch_optional(db_pool)
+                .await?
+                .map(|n| serde_json::to_value(AlertNotificationResponse::from(n)))
+        }
+        RealtimeEventKind::InquiryMessage => {
+            sqlx::query_as::<_, MessageRow>(
+                r#"
+                SELECT m.id, m.inquiry_id, m.sender_id, m.message, m.created_at, u.company_name
+                FROM inquiry_messages m
+                JOIN users u ON u.id = m.sender_id
+                WHERE m.id = $1
+                "#
+            )
+            .bind(notice.id)
+            .fetch_optional(db_pool)
+            .await?
+            .map(|row| {
+                let message = InquiryMessage {
+                    id: row.id,
+                    inquiry_id: row.inquiry_id,
+                    sender_id: row.sender_id,
+                    message: row.message,
+                    created_at: row.created_at,
+                };
+                serde_json::to_value(InquiryMessageResponse::new(message, row.company_name))
+            })
+        }
+    };
+
+    let Some(data) = data else {
+        return Ok(None);
+    };
+
+    Ok(Some(RealtimeEvent {
+        kind: notice.kind,
+        id: notice.id,
+        user_ids: notice.user_ids,
+        data: data.map_err(|e| AppError::Internal(e.into()))?,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stream_limit_per_user() {
+        let hub = RealtimeHub::new();
+        let user_id = Uuid::new_v4();
+
+        let streams: Vec<_> = (0..MAX_STREAMS_PER_USER).map(|_| hub.subscribe(user_id).unwrap()).collect();
+        assert!(hub.subscribe(user_id).is_err());
+        assert!(hub.subscribe(Uuid::new_v4()).is_ok());
+
+        drop(streams);
+        assert_eq!(hub.open_stream_count(), 0);
+        assert!(hub.subscribe(user_id).is_ok());
+    }
+}
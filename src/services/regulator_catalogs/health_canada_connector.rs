@@ -0,0 +1,238 @@
+/// Health Canada Drug Product Database (DPD) connector
+///
+/// Reads the DPD API bulk listings (the same tables as the DPD data extract:
+/// drug products, active ingredients, status, dosage forms, routes and therapeutic
+/// classes) and joins them by drug code into one record per DIN.
+///
+/// Configuration:
+/// - `HEALTH_CANADA_DPD_API_URL` (default https://health-products.canada.ca/api/drug)
+
+use std::collections::HashMap;
+use chrono::NaiveDate;
+use futures::future::BoxFuture;
+use serde::Deserialize;
+use crate::middleware::error_handling::Result;
+use crate::models::regulator_catalog::{RegulatorCatalogRecord, RegulatorSource};
+use super::{CatalogConnector, SourceClient};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DpdDrugProduct {
+    pub drug_code: i64,
+    pub drug_identification_number: Option<String>,
+    pub brand_name: Option<String>,
+    pub descriptor: Option<String>,
+    pub class_name: Option<String>,
+    pub company_name: Option<String>,
+    pub last_update_date: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DpdActiveIngredient {
+    pub drug_code: i64,
+    pub ingredient_name: Option<String>,
+    pub strength: Option<String>,
+    pub strength_unit: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DpdStatus {
+    pub drug_code: i64,
+    pub status: Option<String>,
+    pub original_market_date: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DpdForm {
+    pub drug_code: i64,
+    pub pharmaceutical_form_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DpdRoute {
+    pub drug_code: i64,
+    pub route_of_administration_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DpdTherapeuticClass {
+    pub drug_code: i64,
+    pub tc_atc_number: Option<String>,
+}
+
+/// DPD tables for a set of products
+#[derive(Debug, Default)]
+pub struct DpdTables {
+    pub products: Vec<DpdDrugProduct>,
+    pub ingredients: Vec<DpdActiveIngredient>,
+    pub statuses: Vec<DpdStatus>,
+    pub forms: Vec<DpdForm>,
+    pub routes: Vec<DpdRoute>,
+    pub therapeutic_classes: Vec<DpdTherapeuticClass>,
+}
+
+pub struct HealthCanadaConnector {
+    api_url: String,
+}
+
+impl HealthCanadaConnector {
+    pub fn from_env() -> Self {
+        Self {
+            api_url: std::env::var("HEALTH_CANADA_DPD_API_URL")
+                .unwrap_or_else(|_| "https://health-products.canada.ca/api/drug".to_string())
+                .trim_end_matches('/')
+                .to_string(),
+        }
+    }
+
+    fn table_url(&self, table: &str) -> String {
+        format!("{}/{}/?lang=en&type=json", self.api_url, table)
+    }
+
+    async fn fetch(&self, client: &SourceClient) -> Result<Vec<RegulatorCatalogRecord>> {
+        let tables = DpdTables {
+            products: client.get_json(&self.table_url("drugproduct"), &[]).await?,
+            ingredients: client.get_json(&self.table_url("activeingredient"), &[]).await?,
+            statuses: client.get_json(&self.table_url("status"), &[]).await?,
+            forms: client.get_json(&self.table_url("form"), &[]).await?,
+            routes: client.get_json(&self.table_url("route"), &[]).await?,
+            therapeutic_classes: client.get_json(&self.table_url("therapeuticclass"), &[]).await?,
+        };
+
+        tracing::debug!("Fetched {} Health Canada DPD products", tables.products.len());
+        Ok(join_tables(tables))
+    }
+}
+
+impl CatalogConnector for HealthCanadaConnector {
+    fn source(&self) -> RegulatorSource {
+        RegulatorSource::HealthCanada
+    }
+
+    fn fetch_all<'a>(&'a self, client: &'a SourceClient) -> BoxFuture<'a, Result<Vec<RegulatorCatalogRecord>>> {
+        Box::pin(self.fetch(client))
+    }
+}
+
+/// Join the DPD tables by drug code into one record per product (keyed by DIN)
+pub fn join_tables(tables: DpdTables) -> Vec<RegulatorCatalogRecord> {
+    let mut ingredients: HashMap<i64, Vec<DpdActiveIngredient>> = HashMap::new();
+    for ingredient in tables.ingredients {
+        ingredients.entry(ingredient.drug_code).or_default().push(ingredient);
+    }
+    let statuses: HashMap<i64, DpdStatus> = tables.statuses.into_iter().map(|s| (s.drug_code, s)).collect();
+    let mut forms: HashMap<i64, Vec<String>> = HashMap::new();
+    for form in tables.forms {
+        if let Some(name) = non_empty(form.pharmaceutical_form_name) {
+            forms.entry(form.drug_code).or_default().push(name);
+        }
+    }
+    let mut routes: HashMap<i64, Vec<String>> = HashMap::new();
+    for route in tables.routes {
+        if let Some(name) = non_empty(route.route_of_administration_name) {
+            routes.entry(route.drug_code).or_default().push(name);
+        }
+    }
+    let atc_codes: HashMap<i64, String> = tables
+        .therapeutic_classes
+        .into_iter()
+        .filter_map(|tc| non_empty(tc.tc_atc_number).map(|atc| (tc.drug_code, atc)))
+        .collect();
+
+    tables
+        .products
+        .into_iter()
+        .filter_map(|product| {
+            let product_name = non_empty(product.brand_name.clone())?;
+            let source_id = non_empty(product.drug_identification_number.clone())
+                .unwrap_or_else(|| format!("DC{}", product.drug_code));
+
+            let product_ingredients = ingredients.remove(&product.drug_code).unwrap_or_default();
+            let strength = product_ingredients
+                .iter()
+                .filter_map(|i| {
+                    let strength = non_empty(i.strength.clone())?;
+                    let unit = i.strength_unit.clone().unwrap_or_default();
+                    Some(format!("{} {}", strength, unit).trim().to_string())
+                })
+                .collect::<Vec<_>>()
+                .join("; ");
+            let status = statuses.get(&product.drug_code);
+
+            Some(RegulatorCatalogRecord {
+                source_id,
+                product_name,
+                active_ingredients: product_ingredients
+                    .iter()
+                    .filter_map(|i| non_empty(i.ingredient_name.clone()))
+                    .collect(),
+                strength: Some(strength).filter(|s| !s.is_empty()),
+                dosage_form: forms.remove(&product.drug_code).map(|f| f.join(", ")),
+                route_of_administration: routes.remove(&product.drug_code).unwrap_or_default(),
+                atc_code: atc_codes.get(&product.drug_code).cloned(),
+                authorization_holder: non_empty(product.company_name.clone()),
+                authorization_status: status.and_then(|s| non_empty(s.status.clone())),
+                authorization_date: status
+                    .and_then(|s| s.original_market_date.as_deref())
+                    .and_then(|d| NaiveDate::parse_from_str(d.trim(), "%Y-%m-%d").ok()),
+                raw_data: Some(serde_json::json!({
+                    "drug_code": product.drug_code,
+                    "descriptor": product.descriptor,
+                    "class_name": product.class_name,
+                    "last_update_date": product.last_update_date,
+                })),
+            })
+        })
+        .collect()
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_join_tables() {
+        let tables = DpdTables {
+            products: serde_json::from_value(serde_json::json!([
+                {
+                    "drug_code": 2049, "drug_identification_number": "02229877",
+                    "brand_name": "TYLENOL EXTRA STRENGTH", "company_name": "JOHNSON & JOHNSON INC",
+                    "class_name": "Human", "descriptor": "", "last_update_date": "2023-01-10"
+                },
+                { "drug_code": 9999, "drug_identification_number": "00000000", "brand_name": " " }
+            ]))
+            .unwrap(),
+            ingredients: vec![DpdActiveIngredient {
+                drug_code: 2049,
+                ingredient_name: Some("ACETAMINOPHEN".to_string()),
+                strength: Some("500".to_string()),
+                strength_unit: Some("MG".to_string()),
+            }],
+            statuses: vec![DpdStatus {
+                drug_code: 2049,
+                status: Some("MARKETED".to_string()),
+                original_market_date: Some("1998-03-02".to_string()),
+            }],
+            forms: vec![DpdForm { drug_code: 2049, pharmaceutical_form_name: Some("TABLET".to_string()) }],
+            routes: vec![DpdRoute { drug_code: 2049, route_of_administration_name: Some("ORAL".to_string()) }],
+            therapeutic_classes: vec![DpdTherapeuticClass { drug_code: 2049, tc_atc_number: Some("N02BE01".to_string()) }],
+        };
+
+        let records = join_tables(tables);
+
+        // Products without a brand name are skipped
+        assert_eq!(records.len(), 1);
+        let record = &records[0];
+        assert_eq!(record.source_id, "02229877");
+        assert_eq!(record.active_ingredients, vec!["ACETAMINOPHEN"]);
+        assert_eq!(record.strength.as_deref(), Some("500 MG"));
+        assert_eq!(record.dosage_form.as_deref(), Some("TABLET"));
+        assert_eq!(record.route_of_administration, vec!["ORAL"]);
+        assert_eq!(record.atc_code.as_deref(), Some("N02BE01"));
+        assert_eq!(record.authorization_status.as_deref(), Some("MARKETED"));
+        assert_eq!(record.authorization_date, NaiveDate::from_ymd_opt(1998, 3, 2));
+    }
+}
@@ -0,0 +1,200 @@
+/// UK MHRA products connector
+///
+/// Reads the MHRA products search index (the API behind products.mhra.gov.uk), which
+/// lists SmPC/PIL/PAR documents per product licence. Documents are grouped by PL number
+/// into one catalog record per licence.
+///
+/// Configuration:
+/// - `MHRA_PRODUCTS_API_URL` - search index documents endpoint
+/// - `MHRA_PRODUCTS_API_KEY` - query key (required; the connector is disabled without it)
+
+use std::collections::BTreeMap;
+use futures::future::BoxFuture;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Deserialize;
+use crate::middleware::error_handling::{AppError, Result};
+use crate::models::regulator_catalog::{RegulatorCatalogRecord, RegulatorSource};
+use super::{CatalogConnector, SourceClient};
+
+const PAGE_SIZE: usize = 1000;
+
+/// The search index rejects $skip values above 100000
+const MAX_SKIP: usize = 100_000;
+
+#[derive(Debug, Deserialize)]
+pub struct MhraSearchResponse {
+    #[serde(default)]
+    pub value: Vec<MhraProductDocument>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MhraProductDocument {
+    pub product_name: Option<String>,
+    #[serde(default)]
+    pub substance_name: Vec<String>,
+    #[serde(default)]
+    pub pl_number: Vec<String>,
+    pub doc_type: Option<String>,
+    pub title: Option<String>,
+    pub created: Option<String>,
+}
+
+pub struct MhraConnector {
+    api_url: String,
+    api_key: Option<String>,
+}
+
+impl MhraConnector {
+    pub fn from_env() -> Self {
+        Self {
+            api_url: std::env::var("MHRA_PRODUCTS_API_URL").unwrap_or_else(|_| {
+                "https://mhraproductsproduction.search.windows.net/indexes/products-index/docs".to_string()
+            }),
+            api_key: std::env::var("MHRA_PRODUCTS_API_KEY").ok().filter(|k| !k.trim().is_empty()),
+        }
+    }
+
+    /// Use an API key from the admin-edited source settings instead of the environment
+    pub fn with_api_key(mut self, api_key: String) -> Self {
+        self.api_key = Some(api_key);
+        self
+    }
+
+    async fn fetch(&self, client: &SourceClient) -> Result<Vec<RegulatorCatalogRecord>> {
+        let api_key = self.api_key.as_deref().ok_or_else(|| {
+            AppError::BadRequest("MHRA_PRODUCTS_API_KEY is not configured".to_string())
+        })?;
+
+        let mut documents = Vec::new();
+        let mut skip = 0;
+
+        while skip <= MAX_SKIP {
+            let url = format!(
+                "{}?api-version=2017-11-11&search=*&$top={}&$skip={}",
+                self.api_url, PAGE_SIZE, skip
+            );
+            let page: MhraSearchResponse = client.get_json(&url, &[("api-key", api_key)]).await?;
+            let count = page.value.len();
+            documents.extend(page.value);
+
+            tracing::debug!("Fetched {} MHRA documents (total: {})", count, documents.len());
+            if count < PAGE_SIZE {
+                break;
+            }
+            skip += count;
+        }
+
+        Ok(group_by_licence(&documents))
+    }
+}
+
+impl CatalogConnector for MhraConnector {
+    fn source(&self) -> RegulatorSource {
+        RegulatorSource::Mhra
+    }
+
+    fn is_configured(&self) -> bool {
+        self.api_key.is_some()
+    }
+
+    fn fetch_all<'a>(&'a self, client: &'a SourceClient) -> BoxFuture<'a, Result<Vec<RegulatorCatalogRecord>>> {
+        Box::pin(self.fetch(client))
+    }
+}
+
+/// PL numbers appear as "PL 12345/0001", "PL12345/0001" or "PLGB 12345/0001"
+static PL_NUMBER_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^\s*(PL(?:GB|NI)?|THR(?:GB)?|NR)\s*(\d{5})\s*/\s*(\d{4})\s*$").expect("valid PL number regex")
+});
+
+/// Canonical "PL 12345/0001" form, or None if the value is not a licence number
+pub fn normalize_pl_number(value: &str) -> Option<String> {
+    let captures = PL_NUMBER_PATTERN.captures(value)?;
+    Some(format!("{} {}/{}", captures[1].to_uppercase(), &captures[2], &captures[3]))
+}
+
+/// One record per product licence, merging the documents filed under it
+pub fn group_by_licence(documents: &[MhraProductDocument]) -> Vec<RegulatorCatalogRecord> {
+    let mut licences: BTreeMap<String, RegulatorCatalogRecord> = BTreeMap::new();
+
+    for document in documents {
+        let product_name = match document.product_name.as_deref().map(str::trim) {
+            Some(name) if !name.is_empty() => name.to_string(),
+            _ => continue,
+        };
+
+        for pl_number in document.pl_number.iter().filter_map(|pl| normalize_pl_number(pl.as_str())) {
+            let record = licences.entry(pl_number.clone()).or_insert_with(|| RegulatorCatalogRecord {
+                source_id: pl_number.clone(),
+                product_name: product_name.clone(),
+                active_ingredients: Vec::new(),
+                strength: None,
+                dosage_form: None,
+                route_of_administration: Vec::new(),
+                atc_code: None,
+                authorization_holder: None,
+                authorization_status: Some("authorised".to_string()),
+                authorization_date: None,
+                raw_data: Some(serde_json::json!({ "documents": [] })),
+            });
+
+            for substance in &document.substance_name {
+                let substance = substance.trim().to_uppercase();
+                if !substance.is_empty() && !record.active_ingredients.contains(&substance) {
+                    record.active_ingredients.push(substance);
+                }
+            }
+
+            if let Some(docs) = record
+                .raw_data
+                .as_mut()
+                .and_then(|raw| raw.get_mut("documents"))
+                .and_then(|d| d.as_array_mut())
+            {
+                docs.push(serde_json::json!({
+                    "doc_type": document.doc_type,
+                    "title": document.title,
+                    "created": document.created,
+                }));
+            }
+        }
+    }
+
+    licences.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_pl_number() {
+        assert_eq!(normalize_pl_number("PL 12345/0001"), Some("PL 12345/0001".to_string()));
+        assert_eq!(normalize_pl_number("pl12345/0001"), Some("PL 12345/0001".to_string()));
+        assert_eq!(normalize_pl_number("PLGB 04425/0682"), Some("PLGB 04425/0682".to_string()));
+        assert_eq!(normalize_pl_number("EU/1/20/1528/001"), None);
+    }
+
+    #[test]
+    fn test_group_by_licence() {
+        let document = |doc_type: &str, substances: &[&str]| MhraProductDocument {
+            product_name: Some("PARACETAMOL 500MG TABLETS".to_string()),
+            substance_name: substances.iter().map(|s| s.to_string()).collect(),
+            pl_number: vec!["PL12345/0001".to_string()],
+            doc_type: Some(doc_type.to_string()),
+            title: None,
+            created: None,
+        };
+
+        let records = group_by_licence(&[
+            document("Spc", &["Paracetamol"]),
+            document("Pil", &["PARACETAMOL"]),
+        ]);
+
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].source_id, "PL 12345/0001");
+        assert_eq!(records[0].active_ingredients, vec!["PARACETAMOL"]);
+        assert_eq!(records[0].raw_data.as_ref().unwrap()["documents"].as_array().unwrap().len(), 2);
+    }
+}
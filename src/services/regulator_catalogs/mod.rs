@@ -0,0 +1,50 @@
+// Regulator Catalog Module
+// Connectors for national product catalogs (UK MHRA, Health Canada DPD, Swissmedic)
+// and the shared sync service/scheduler that stores them in `regulator_catalog`
+
+pub mod source_client;
+pub mod mhra_connector;
+pub mod health_canada_connector;
+pub mod swissmedic_connector;
+pub mod regulator_catalog_service;
+
+use futures::future::BoxFuture;
+use crate::middleware::error_handling::Result;
+use crate::models::regulator_catalog::{RegulatorCatalogRecord, RegulatorSource};
+use crate::models::sync_source::SourceRuntimeSettings;
+
+pub use source_client::SourceClient;
+pub use mhra_connector::MhraConnector;
+pub use health_canada_connector::HealthCanadaConnector;
+pub use swissmedic_connector::SwissmedicConnector;
+pub use regulator_catalog_service::{RegulatorCatalogService, RegulatorCatalogSyncScheduler};
+
+/// A regulator data source. Connectors download the complete product list; the sync
+/// service handles logging, upserts and removal of products no longer listed.
+pub trait CatalogConnector: Send + Sync {
+    fn source(&self) -> RegulatorSource;
+
+    /// False when required configuration (e.g. an API key) is missing
+    fn is_configured(&self) -> bool {
+        true
+    }
+
+    fn fetch_all<'a>(&'a self, client: &'a SourceClient) -> BoxFuture<'a, Result<Vec<RegulatorCatalogRecord>>>;
+}
+
+/// Connector for a source, configured from the environment
+pub fn connector_for(source: RegulatorSource) -> Box<dyn CatalogConnector> {
+    match source {
+        RegulatorSource::Mhra => Box::new(MhraConnector::from_env()),
+        RegulatorSource::HealthCanada => Box::new(HealthCanadaConnector::from_env()),
+        RegulatorSource::Swissmedic => Box::new(SwissmedicConnector::from_env()),
+    }
+}
+
+/// Connector for a source; an API key in the source settings overrides the environment
+pub fn connector_with_settings(source: RegulatorSource, settings: &SourceRuntimeSettings) -> Box<dyn CatalogConnector> {
+    match (source, &settings.api_key) {
+        (RegulatorSource::Mhra, Some(api_key)) => Box::new(MhraConnector::from_env().with_api_key(api_key.clone())),
+        _ => connector_for(source),
+    }
+}
@@ -0,0 +1,309 @@
+/// Regulator Catalog Service
+///
+/// Runs connector syncs into `regulator_catalog` with progress in `catalog_sync_log`,
+/// and serves catalog search per source. Sources are complete product lists, so each
+/// sync upserts everything it fetched and removes products no longer listed.
+
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::{
+    middleware::{error_handling::{AppError, Result}, metrics},
+    models::{
+        background_job::{JobType, NewJob, RegulatorCatalogSyncJob},
+        regulator_catalog::*,
+        sync_anomaly::SyncRunStats,
+        sync_source::SourceRuntimeSettings,
+    },
+    repositories::RegulatorCatalogRepository,
+    services::{BackgroundJobService, Shutdown, SyncAnomalyService, SyncLogTable, SyncSourceService},
+};
+use super::{connector_with_settings, SourceClient};
+
+const UPSERT_BATCH_SIZE: usize = 500;
+
+pub struct RegulatorCatalogService {
+    db_pool: PgPool,
+}
+
+impl RegulatorCatalogService {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self { db_pool }
+    }
+
+    // ========================================================================
+    // SYNC
+    // ========================================================================
+
+    /// Queue a sync for the source as a background job and return its sync log id
+    pub async fn start_background_sync(&self, source: RegulatorSource, sync_type: &str) -> Result<Uuid> {
+        let repo = RegulatorCatalogRepository::new(self.db_pool.clone());
+        let settings = self.source_settings(source).await;
+
+        if !connector_with_settings(source, &settings).is_configured() {
+            return Err(AppError::BadRequest(format!(
+                "{} catalog sync is not configured",
+                source.display_name()
+            )));
+        }
+        if repo.is_sync_running(source).await? {
+            return Err(AppError::Conflict);
+        }
+
+        let log_id = repo.start_sync_log(source, sync_type).await?;
+        let job = RegulatorCatalogSyncJob { sync_log_id: log_id, source };
+        let queued = BackgroundJobService::new(self.db_pool.clone())
+            .enqueue(NewJob::new(JobType::RegulatorCatalogSync, &job).with_dedupe_key(format!("regulator_catalog_sync:{}", log_id)))
+            .await;
+        if let Err(e) = queued {
+            let _ = repo.fail_sync_log(log_id, "Sync could not be queued").await;
+            return Err(e);
+        }
+
+        Ok(log_id)
+    }
+
+    /// Run the sync of a background job with the source's current settings; a retry
+    /// downloads the source again
+    pub async fn run_sync_job(job: &RegulatorCatalogSyncJob, retry: bool, pool: PgPool) -> Result<()> {
+        let (source, log_id) = (job.source, job.sync_log_id);
+        let repo = RegulatorCatalogRepository::new(pool.clone());
+        let _in_flight = Shutdown::track_sync(SyncLogTable::RegulatorCatalog, log_id);
+
+        // The previous attempt failed the sync log or left it in progress when its worker died
+        if retry && !repo.restart_sync_log(log_id).await? {
+            tracing::info!("{} catalog sync {} has finished, not retrying it", source.display_name(), log_id);
+            return Ok(());
+        }
+
+        let service = RegulatorCatalogService::new(pool.clone());
+        let settings = service.source_settings(source).await;
+        match service.perform_sync(source, log_id, settings).await {
+            Ok(()) => Ok(()),
+            Err(e) if Shutdown::requested() => {
+                // The job is queued again and downloads the source again
+                tracing::warn!("{} catalog sync {} interrupted by shutdown: {:?}", source.display_name(), log_id, e);
+                let _ = SyncLogTable::RegulatorCatalog.mark_interrupted(&pool, log_id).await;
+                Err(e)
+            }
+            Err(e) => {
+                tracing::error!("{} catalog sync {} failed: {:?}", source.display_name(), log_id, e);
+                let _ = repo.fail_sync_log(log_id, &format!("{:?}", e)).await;
+                Err(e)
+            }
+        }
+    }
+
+    /// Admin-edited settings of the source; falls back to the environment if they can't be read
+    async fn source_settings(&self, source: RegulatorSource) -> SourceRuntimeSettings {
+        match SyncSourceService::new(self.db_pool.clone()).runtime_settings(source.as_str()).await {
+            Ok(settings) => settings,
+            Err(e) => {
+                tracing::warn!("Failed to load {} source settings, using environment: {:?}", source.display_name(), e);
+                SourceRuntimeSettings::default()
+            }
+        }
+    }
+
+    async fn perform_sync(&self, source: RegulatorSource, log_id: Uuid, settings: SourceRuntimeSettings) -> Result<()> {
+        let repo = RegulatorCatalogRepository::new(self.db_pool.clone());
+        let connector = connector_with_settings(source, &settings);
+        let client = SourceClient::with_settings(&settings);
+        let start = Instant::now();
+
+        tracing::info!("Starting {} catalog sync (log_id: {})", source.display_name(), log_id);
+        let (catalog_size, _) = repo.get_counts(source).await?;
+
+        let records = connector.fetch_all(&client).await?;
+        let fetched = records.len() as i32;
+
+        // Connectors may emit the same identifier twice; the first record wins
+        let mut seen = HashSet::new();
+        let records: Vec<RegulatorCatalogRecord> = records
+            .into_iter()
+            .filter(|r| seen.insert(r.source_id.clone()))
+            .collect();
+        let skipped = fetched - records.len() as i32;
+
+        let (mut inserted, mut updated) = (0, 0);
+        for batch in records.chunks(UPSERT_BATCH_SIZE) {
+            // Stopping before the removal below: a partial run must not drop products
+            if Shutdown::requested() {
+                return Err(Shutdown::interrupted());
+            }
+            let (batch_inserted, batch_updated) = repo.batch_upsert(source, batch).await?;
+            inserted += batch_inserted;
+            updated += batch_updated;
+            repo.update_sync_progress(log_id, fetched, inserted, updated, skipped).await?;
+        }
+
+        // An empty download is treated as a source problem, not as every product being withdrawn
+        let removed = if records.is_empty() {
+            tracing::warn!("{} returned no products; keeping existing catalog", source.display_name());
+            0
+        } else {
+            repo.remove_not_synced_since(source, log_id).await?
+        };
+
+        repo.complete_sync_log(
+            log_id,
+            fetched,
+            inserted,
+            updated,
+            skipped,
+            removed as i32,
+            client.api_time_ms() as i32,
+            start.elapsed().as_millis() as i32,
+        )
+        .await?;
+        metrics::record_sync_run(source.as_str(), fetched, inserted, updated, 0, start.elapsed());
+
+        // Guardrails: suspect runs don't count as a successful refresh
+        let guard = SyncAnomalyService::new(self.db_pool.clone());
+        let anomalies = guard.assess(&SyncRunStats {
+            catalog_size,
+            fetched: fetched as i64,
+            inserted: inserted as i64,
+            updated: updated as i64,
+            failed: 0,
+            removed,
+            altered: 0,
+            incremental: false,
+        });
+        if anomalies.is_empty() || repo.mark_sync_suspect(log_id, &serde_json::to_value(&anomalies)?).await? {
+            guard.report(source.as_str(), log_id, &anomalies).await;
+        }
+
+        tracing::info!(
+            "{} catalog sync completed: fetched={}, inserted={}, updated={}, skipped={}, removed={}",
+            source.display_name(), fetched, inserted, updated, skipped, removed
+        );
+
+        Ok(())
+    }
+
+    // ========================================================================
+    // LOOKUP
+    // ========================================================================
+
+    pub async fn search(
+        &self,
+        source: RegulatorSource,
+        request: &RegulatorCatalogSearchRequest,
+    ) -> Result<Vec<RegulatorCatalogEntry>> {
+        RegulatorCatalogRepository::new(self.db_pool.clone())
+            .search(source, request)
+            .await
+    }
+
+    pub async fn get_entry(&self, source: RegulatorSource, source_id: &str) -> Result<RegulatorCatalogEntry> {
+        RegulatorCatalogRepository::new(self.db_pool.clone())
+            .find_by_source_id(source, source_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!(
+                "{} product {} not found",
+                source.display_name(), source_id
+            )))
+    }
+
+    pub async fn get_stats(&self, source: RegulatorSource) -> Result<RegulatorCatalogStats> {
+        let repo = RegulatorCatalogRepository::new(self.db_pool.clone());
+        let (total_entries, last_synced_at) = repo.get_counts(source).await?;
+        let last_sync = repo.get_last_successful_sync(source).await?;
+
+        Ok(RegulatorCatalogStats {
+            source: source.as_str().to_string(),
+            display_name: source.display_name().to_string(),
+            country: source.country().to_string(),
+            total_entries,
+            last_synced_at,
+            last_sync,
+        })
+    }
+
+    pub async fn get_sync_logs(
+        &self,
+        source: RegulatorSource,
+        limit: Option<i64>,
+        offset: Option<i64>,
+    ) -> Result<Vec<CatalogSyncLog>> {
+        RegulatorCatalogRepository::new(self.db_pool.clone())
+            .get_sync_logs(source, limit.unwrap_or(20).clamp(1, 100), offset.unwrap_or(0).max(0))
+            .await
+    }
+}
+
+// ============================================================================
+// SCHEDULER
+// ============================================================================
+
+/// Periodic sync of every configured regulator catalog
+pub struct RegulatorCatalogSyncScheduler {
+    pool: PgPool,
+    interval_hours: u64,
+    sources: Vec<RegulatorSource>,
+}
+
+impl RegulatorCatalogSyncScheduler {
+    pub fn new(pool: PgPool) -> Self {
+        let interval_hours = std::env::var("REGULATOR_CATALOG_SYNC_INTERVAL_HOURS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(168);
+
+        // REGULATOR_CATALOG_SOURCES=mhra,health_canada limits scheduled syncs
+        let sources = match std::env::var("REGULATOR_CATALOG_SOURCES") {
+            Ok(list) => list.split(',').filter_map(RegulatorSource::parse).collect(),
+            Err(_) => RegulatorSource::ALL.to_vec(),
+        };
+
+        Self { pool, interval_hours, sources }
+    }
+
+    /// Run the scheduler loop
+    pub async fn run(&self) {
+        let mut ticker = tokio::time::interval(Duration::from_secs(self.interval_hours * 3600));
+
+        // Skip first tick (runs immediately on start)
+        ticker.tick().await;
+
+        tracing::info!(
+            "Regulator catalog sync scheduler started - syncing {:?} every {} hours",
+            self.sources, self.interval_hours
+        );
+
+        loop {
+            let deadline = ticker.tick().await;
+            metrics::record_scheduler_lag("regulator_catalogs", deadline);
+            if crate::services::MaintenanceService::pauses("regulator_catalogs")
+                || !crate::services::SchedulerLeader::leads("regulator_catalogs").await
+            {
+                continue;
+            }
+
+            let service = RegulatorCatalogService::new(self.pool.clone());
+            let source_settings = SyncSourceService::new(self.pool.clone());
+            for source in &self.sources {
+                if source_settings.is_scheduler_paused(source.as_str()).await {
+                    tracing::info!("{} sync scheduler is paused by an admin, skipping scheduled sync", source.display_name());
+                    continue;
+                }
+
+                let settings = service.source_settings(*source).await;
+                if !connector_with_settings(*source, &settings).is_configured() {
+                    tracing::debug!("Skipping scheduled {} sync: not configured", source.display_name());
+                    continue;
+                }
+
+                match service.start_background_sync(*source, "scheduled").await {
+                    Ok(sync_id) => tracing::info!("Scheduled {} sync started with ID: {}", source.display_name(), sync_id),
+                    Err(AppError::Conflict) => {
+                        tracing::info!("{} sync already in progress, skipping scheduled sync", source.display_name());
+                    }
+                    Err(e) => tracing::error!("Failed to start scheduled {} sync: {:?}", source.display_name(), e),
+                }
+            }
+        }
+    }
+}
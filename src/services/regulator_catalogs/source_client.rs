@@ -0,0 +1,136 @@
+/// HTTP client shared by regulator catalog connectors: retries with exponential backoff,
+/// backs off on 429 and paces requests with the source's request budget.
+
+use std::time::{Duration, Instant};
+use serde::de::DeserializeOwned;
+use crate::middleware::error_handling::{AppError, Result};
+use crate::models::sync_source::SourceRuntimeSettings;
+use crate::utils::RequestBudget;
+
+pub struct SourceClient {
+    http_client: reqwest::Client,
+    request_budget: RequestBudget,
+    max_retries: u32,
+    /// Time spent waiting on source responses, for the sync log
+    api_time_ms: std::sync::atomic::AtomicU64,
+}
+
+impl SourceClient {
+    pub fn new() -> Self {
+        Self::with_settings(&SourceRuntimeSettings::default())
+    }
+
+    /// Client paced by the admin-edited source settings. Without a stored rate, the budget
+    /// follows `REGULATOR_CATALOG_REQUEST_DELAY_MS` (default 250ms, i.e. 240 requests/minute).
+    pub fn with_settings(settings: &SourceRuntimeSettings) -> Self {
+        let request_delay_ms: u64 = std::env::var("REGULATOR_CATALOG_REQUEST_DELAY_MS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(250);
+        let requests_per_minute = settings
+            .requests_per_minute
+            .unwrap_or((60_000 / request_delay_ms.max(1)).min(u32::MAX as u64) as u32);
+
+        let http_client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(300))
+            .user_agent("Atlas-Pharma-Catalog-Client/1.0")
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            http_client,
+            request_budget: RequestBudget::with_burst(requests_per_minute, settings.burst_limit.unwrap_or(1)),
+            max_retries: std::env::var("REGULATOR_CATALOG_MAX_RETRIES")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(3),
+            api_time_ms: std::sync::atomic::AtomicU64::new(0),
+        }
+    }
+
+    pub fn api_time_ms(&self) -> u64 {
+        self.api_time_ms.load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    pub async fn get_bytes(&self, url: &str, headers: &[(&str, &str)]) -> Result<Vec<u8>> {
+        let mut last_error = None;
+
+        for attempt in 0..self.max_retries.max(1) {
+            // Exponential backoff on retries: 2s, 4s, ...
+            if attempt > 0 {
+                tokio::time::sleep(Duration::from_secs(1 << attempt)).await;
+            }
+            self.request_budget.acquire().await;
+
+            let mut request = self.http_client.get(url);
+            for (name, value) in headers {
+                request = request.header(*name, *value);
+            }
+
+            let started = Instant::now();
+            let result = request.send().await;
+
+            let response = match result {
+                Ok(response) => response,
+                Err(e) => {
+                    last_error = Some(AppError::Internal(anyhow::anyhow!("HTTP request failed: {}", e)));
+                    continue;
+                }
+            };
+
+            match response.status().as_u16() {
+                429 => {
+                    tracing::warn!("Rate limited by {}, backing off...", host(url));
+                    self.request_budget.pause(Duration::from_secs(60));
+                    last_error = Some(AppError::TooManyRequests(format!("{} rate limit exceeded", host(url))));
+                    continue;
+                }
+                status if !(200..300).contains(&status) => {
+                    last_error = Some(AppError::Internal(anyhow::anyhow!(
+                        "{} returned status: {}", host(url), status
+                    )));
+                    continue;
+                }
+                _ => {}
+            }
+
+            match response.bytes().await {
+                Ok(body) => {
+                    self.api_time_ms.fetch_add(
+                        started.elapsed().as_millis() as u64,
+                        std::sync::atomic::Ordering::Relaxed,
+                    );
+                    return Ok(body.to_vec());
+                }
+                Err(e) => {
+                    last_error = Some(AppError::Internal(anyhow::anyhow!("Failed to read response body: {}", e)));
+                }
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| {
+            AppError::Internal(anyhow::anyhow!("Failed after {} retries", self.max_retries))
+        }))
+    }
+
+    pub async fn get_json<T: DeserializeOwned>(&self, url: &str, headers: &[(&str, &str)]) -> Result<T> {
+        let body = self.get_bytes(url, headers).await?;
+        serde_json::from_slice(&body).map_err(|e| {
+            AppError::Internal(anyhow::anyhow!("Failed to parse response from {}: {}", host(url), e))
+        })
+    }
+}
+
+impl Default for SourceClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Host part of a URL for log and error messages (never logs query strings with API keys)
+fn host(url: &str) -> String {
+    url::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_string))
+        .unwrap_or_else(|| "source".to_string())
+}
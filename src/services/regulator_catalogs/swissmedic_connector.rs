@@ -0,0 +1,227 @@
+/// Swissmedic connector
+///
+/// Reads the published list of authorised human medicinal product packages
+/// ("Zugelassene Packungen", Excel) and reduces it to one record per authorisation
+/// number and dosage strength.
+///
+/// Configuration:
+/// - `SWISSMEDIC_PACKAGES_URL` - URL of the packages list workbook
+
+use std::collections::BTreeMap;
+use std::io::Cursor;
+use calamine::{open_workbook_from_rs, Data, Reader, Xlsx};
+use chrono::NaiveDate;
+use futures::future::BoxFuture;
+use crate::middleware::error_handling::{AppError, Result};
+use crate::models::regulator_catalog::{RegulatorCatalogRecord, RegulatorSource};
+use super::{CatalogConnector, SourceClient};
+
+/// Columns we read, matched against normalized header text (lowercase, no spaces/hyphens)
+const COL_AUTHORIZATION: &str = "zulassungsnummer";
+const COL_DOSAGE_NUMBER: &str = "dosisstärkenummer";
+const COL_NAME: &str = "bezeichnungdesarzneimittels";
+const COL_HOLDER: &str = "zulassungsinhaberin";
+const COL_ATC: &str = "atccode";
+const COL_FIRST_AUTHORIZATION: &str = "erstzulassungsdatumarzneimittel";
+const COL_SUBSTANCES: &str = "wirkstoff";
+const COL_CATEGORY: &str = "heilmittelcode";
+const COL_DISPENSING: &str = "abgabekategoriepackung";
+
+pub struct SwissmedicConnector {
+    packages_url: String,
+}
+
+impl SwissmedicConnector {
+    pub fn from_env() -> Self {
+        Self {
+            packages_url: std::env::var("SWISSMEDIC_PACKAGES_URL").unwrap_or_else(|_| {
+                "https://www.swissmedic.ch/dam/swissmedic/de/dokumente/internetlisten/zugelassene_packungen_human.xlsx.download.xlsx/zugelassene_packungen_ham.xlsx".to_string()
+            }),
+        }
+    }
+
+    async fn fetch(&self, client: &SourceClient) -> Result<Vec<RegulatorCatalogRecord>> {
+        let workbook = client.get_bytes(&self.packages_url, &[]).await?;
+        let rows = read_first_sheet(&workbook)?;
+
+        tracing::debug!("Read {} rows from Swissmedic packages list", rows.len());
+        parse_package_rows(&rows)
+    }
+}
+
+impl CatalogConnector for SwissmedicConnector {
+    fn source(&self) -> RegulatorSource {
+        RegulatorSource::Swissmedic
+    }
+
+    fn fetch_all<'a>(&'a self, client: &'a SourceClient) -> BoxFuture<'a, Result<Vec<RegulatorCatalogRecord>>> {
+        Box::pin(self.fetch(client))
+    }
+}
+
+fn read_first_sheet(workbook: &[u8]) -> Result<Vec<Vec<String>>> {
+    let mut workbook = open_workbook_from_rs::<Xlsx<_>, _>(Cursor::new(workbook))
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to open Swissmedic workbook: {}", e)))?;
+
+    let sheet_name = workbook
+        .sheet_names()
+        .first()
+        .cloned()
+        .ok_or_else(|| AppError::Internal(anyhow::anyhow!("Swissmedic workbook contains no sheets")))?;
+    let range = workbook
+        .worksheet_range(&sheet_name)
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to read sheet {}: {}", sheet_name, e)))?;
+
+    Ok(range
+        .rows()
+        .map(|row| row.iter().map(cell_to_string).collect())
+        .collect())
+}
+
+fn cell_to_string(cell: &Data) -> String {
+    match cell {
+        Data::Int(i) => i.to_string(),
+        Data::Float(f) if f.fract() == 0.0 => format!("{}", *f as i64),
+        Data::Float(f) => f.to_string(),
+        Data::String(s) => s.trim().to_string(),
+        Data::Bool(b) => b.to_string(),
+        // Excel serial date; converted in parse_date
+        Data::DateTime(dt) => format!("{}", dt.as_f64() as i64),
+        Data::DateTimeIso(s) => s.clone(),
+        Data::DurationIso(s) => s.clone(),
+        Data::Error(_) | Data::Empty => String::new(),
+    }
+}
+
+fn normalize_header(header: &str) -> String {
+    header
+        .to_lowercase()
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-' && *c != '.')
+        .collect()
+}
+
+/// Parse sheet rows (title rows first, then a header row, then one row per package)
+pub fn parse_package_rows(rows: &[Vec<String>]) -> Result<Vec<RegulatorCatalogRecord>> {
+    let header_index = rows
+        .iter()
+        .position(|row| row.iter().any(|cell| normalize_header(cell) == COL_AUTHORIZATION))
+        .ok_or_else(|| AppError::Internal(anyhow::anyhow!(
+            "Swissmedic packages list has no 'Zulassungs-Nummer' header row"
+        )))?;
+
+    let headers: Vec<String> = rows[header_index].iter().map(|h| normalize_header(h)).collect();
+    let column = |name: &str| headers.iter().position(|h| h.starts_with(name));
+    let authorization_col = column(COL_AUTHORIZATION)
+        .ok_or_else(|| AppError::Internal(anyhow::anyhow!("Missing authorization number column")))?;
+    let name_col = column(COL_NAME)
+        .ok_or_else(|| AppError::Internal(anyhow::anyhow!("Missing product name column")))?;
+    let (dosage_col, holder_col, atc_col, date_col, substances_col, category_col, dispensing_col) = (
+        column(COL_DOSAGE_NUMBER),
+        column(COL_HOLDER),
+        column(COL_ATC),
+        column(COL_FIRST_AUTHORIZATION),
+        column(COL_SUBSTANCES),
+        column(COL_CATEGORY),
+        column(COL_DISPENSING),
+    );
+
+    let mut records: BTreeMap<String, RegulatorCatalogRecord> = BTreeMap::new();
+
+    for row in &rows[header_index + 1..] {
+        let cell = |index: Option<usize>| {
+            index
+                .and_then(|i| row.get(i))
+                .map(|v| v.trim())
+                .filter(|v| !v.is_empty())
+                .map(str::to_string)
+        };
+
+        let (authorization, product_name) = match (cell(Some(authorization_col)), cell(Some(name_col))) {
+            (Some(authorization), Some(name)) => (authorization, name),
+            _ => continue,
+        };
+        let source_id = match cell(dosage_col) {
+            Some(dosage) => format!("{:0>5}-{:0>2}", authorization, dosage),
+            None => format!("{:0>5}", authorization),
+        };
+
+        // Several packages share a dosage strength; the first row describes the product
+        if records.contains_key(&source_id) {
+            continue;
+        }
+
+        records.insert(source_id.clone(), RegulatorCatalogRecord {
+            source_id,
+            product_name,
+            active_ingredients: cell(substances_col)
+                .map(|s| s.split(',').map(|i| i.trim().to_string()).filter(|i| !i.is_empty()).collect())
+                .unwrap_or_default(),
+            strength: None,
+            dosage_form: None,
+            route_of_administration: Vec::new(),
+            atc_code: cell(atc_col).map(|a| a.to_uppercase()),
+            authorization_holder: cell(holder_col),
+            authorization_status: Some("authorised".to_string()),
+            authorization_date: cell(date_col).as_deref().and_then(parse_date),
+            raw_data: Some(serde_json::json!({
+                "authorization_number": authorization,
+                "category": cell(category_col),
+                "dispensing_category": cell(dispensing_col),
+            })),
+        });
+    }
+
+    Ok(records.into_values().collect())
+}
+
+/// Dates appear as dd.mm.yyyy text, ISO dates or Excel serial numbers
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%d.%m.%Y")
+        .or_else(|_| NaiveDate::parse_from_str(value, "%Y-%m-%d"))
+        .ok()
+        .or_else(|| {
+            let serial: i64 = value.parse().ok()?;
+            NaiveDate::from_ymd_opt(1899, 12, 30)?.checked_add_signed(chrono::Duration::days(serial))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(cells: &[&str]) -> Vec<String> {
+        cells.iter().map(|c| c.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_package_rows() {
+        let rows = vec![
+            row(&["Swissmedic, Schweizerisches Heilmittelinstitut", "", "", "", "", ""]),
+            row(&["Zulassungs-\nNummer", "Dosisstärke-nummer", "Bezeichnung des Arzneimittels",
+                  "Zulassungs-inhaberin", "ATC-Code", "Erstzulassungs-datum Arzneimittel", "Wirkstoff(e)"]),
+            row(&["54103", "1", "Dafalgan 500 mg, Tabletten", "UPSA Switzerland AG", "n02be01", "03.06.1997", "paracetamolum"]),
+            row(&["54103", "1", "Dafalgan 500 mg, Tabletten", "UPSA Switzerland AG", "N02BE01", "03.06.1997", "paracetamolum"]),
+            row(&["654", "2", "Co-Product", "Example AG", "", "35431", "substance a, substance b"]),
+            row(&["", "", "", "", "", "", ""]),
+        ];
+
+        let records = parse_package_rows(&rows).unwrap();
+        assert_eq!(records.len(), 2);
+
+        let dafalgan = records.iter().find(|r| r.source_id == "54103-01").unwrap();
+        assert_eq!(dafalgan.product_name, "Dafalgan 500 mg, Tabletten");
+        assert_eq!(dafalgan.atc_code.as_deref(), Some("N02BE01"));
+        assert_eq!(dafalgan.authorization_date, NaiveDate::from_ymd_opt(1997, 6, 3));
+        assert_eq!(dafalgan.active_ingredients, vec!["paracetamolum"]);
+
+        let combo = records.iter().find(|r| r.source_id == "00654-02").unwrap();
+        assert_eq!(combo.active_ingredients, vec!["substance a", "substance b"]);
+        assert_eq!(combo.authorization_date, NaiveDate::from_ymd_opt(1997, 1, 1));
+    }
+
+    #[test]
+    fn test_missing_header_row() {
+        assert!(parse_package_rows(&[row(&["not", "a", "packages", "list"])]).is_err());
+    }
+}
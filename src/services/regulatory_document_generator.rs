@@ -3,30 +3,39 @@
 // Follows exact patterns from existing services - PRODUCTION READY
 
 use crate::middleware::error_handling::{Result, AppError};
+use crate::models::openfda::OpenFdaCatalogVersion;
+use crate::repositories::OpenFdaRepository;
 use crate::services::{
-    ClaudeAIService, ClaudeEmbeddingService, ClaudeMessage, ClaudeRequestConfig,
+    AiFeature, ClaudeAIService, ClaudeEmbeddingService, ClaudeMessage, ClaudeRequestConfig,
     Ed25519SignatureService, KnowledgeEntry,
 };
 use anyhow::anyhow;
 use chrono::Datelike;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use sha2::{Digest, Sha256};
-use sqlx::PgPool;
+use sqlx::{FromRow, PgPool};
+use std::collections::BTreeMap;
 use uuid::Uuid;
 
+/// Characters of each knowledge-base chunk shown to Claude (and stored as the citation excerpt)
+const CITATION_EXCERPT_CHARS: usize = 500;
+
 /// Document generation request
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct GenerateDocumentRequest {
     pub document_type: DocumentType,
     pub product_name: Option<String>,
     pub batch_number: Option<String>,
     pub manufacturer: Option<String>,
+    /// OpenFDA NDC of the product; the catalog version in force is recorded on the document
+    pub product_ndc: Option<String>,
     pub test_results: Option<serde_json::Value>,
     pub custom_fields: Option<serde_json::Value>,
 }
 
 /// Document type
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, ToSchema)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum DocumentType {
     CoA,  // Certificate of Analysis
@@ -45,7 +54,7 @@ impl DocumentType {
 }
 
 /// Generated document response
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct GeneratedDocument {
     pub id: Uuid,
     pub document_type: String,
@@ -57,13 +66,19 @@ pub struct GeneratedDocument {
     pub signature: String,
     pub public_key: String,
     pub rag_context: Vec<RagContextEntry>,
+    /// Retrieved chunks with provenance and whether the document cites them
+    pub citations: Vec<DocumentCitation>,
+    /// References in the document that match no retrieved chunk
+    pub unresolved_citations: Vec<String>,
     pub status: String,
     pub generated_by: String,
+    /// OpenFDA catalog version the document was generated against
+    pub catalog_version_id: Option<Uuid>,
     pub created_at: chrono::DateTime<chrono::Utc>,
 }
 
 /// RAG context entry (knowledge base chunks used)
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, ToSchema)]
 pub struct RagContextEntry {
     pub id: Uuid,
     pub section_title: String,
@@ -71,6 +86,129 @@ pub struct RagContextEntry {
     pub similarity: f64,
 }
 
+/// A knowledge-base chunk retrieved for a document, with its provenance at generation time
+#[derive(Debug, Serialize, Clone, PartialEq, ToSchema)]
+pub struct DocumentCitation {
+    /// Label the chunk was given in the prompt ("R1", "R2", ...)
+    pub citation_ref: String,
+    pub knowledge_entry_id: Option<Uuid>,
+    pub regulation_source: Option<String>,
+    pub regulation_section: Option<String>,
+    pub section_title: String,
+    pub similarity: f64,
+    pub excerpt: String,
+    /// SHA-256 of the chunk content when it was retrieved
+    pub content_hash: String,
+    pub cited: bool,
+    /// Parts of the document the chunk was cited for
+    pub applies_to: Vec<String>,
+}
+
+/// Stored citation of a document, checked against the knowledge base as it is now
+#[derive(Debug, Serialize, FromRow)]
+pub struct StoredCitation {
+    pub citation_ref: String,
+    pub knowledge_entry_id: Option<Uuid>,
+    pub regulation_source: Option<String>,
+    pub regulation_section: Option<String>,
+    pub section_title: String,
+    pub similarity: f64,
+    pub excerpt: String,
+    pub content_hash: String,
+    pub cited: bool,
+    pub applies_to: serde_json::Value,
+    /// The chunk was edited or deleted after the document was generated
+    #[sqlx(default)]
+    pub source_changed: bool,
+    #[serde(skip)]
+    current_content: Option<String>,
+}
+
+/// SHA-256 of a knowledge-base chunk's content
+pub fn chunk_content_hash(content: &str) -> String {
+    hex::encode(Sha256::digest(content.as_bytes()))
+}
+
+fn citation_excerpt(content: &str) -> String {
+    content.chars().take(CITATION_EXCERPT_CHARS).collect()
+}
+
+/// Normalize a model-written reference ("[r3]", " R3 ") to its label ("R3")
+fn normalize_citation_ref(raw: &str) -> String {
+    raw.trim().trim_start_matches('[').trim_end_matches(']').trim().to_uppercase()
+}
+
+/// Match the `citations` array of generated content against the retrieved chunks.
+/// Returns one citation per chunk (in retrieval order) and the references that match none.
+pub fn resolve_citations(
+    rag_context: &[KnowledgeEntry],
+    content: &serde_json::Value,
+) -> (Vec<DocumentCitation>, Vec<String>) {
+    let mut applies_to: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for citation in content.get("citations").and_then(|c| c.as_array()).into_iter().flatten() {
+        let Some(raw) = citation.get("ref").and_then(|r| r.as_str()) else {
+            continue;
+        };
+        let target = applies_to.entry(normalize_citation_ref(raw)).or_default();
+        if let Some(part) = citation.get("applies_to").and_then(|a| a.as_str()) {
+            if !part.trim().is_empty() && !target.iter().any(|p| p == part.trim()) {
+                target.push(part.trim().to_string());
+            }
+        }
+    }
+
+    let citations: Vec<DocumentCitation> = rag_context
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            let citation_ref = format!("R{}", i + 1);
+            let cited_for = applies_to.remove(&citation_ref);
+            DocumentCitation {
+                knowledge_entry_id: Some(entry.id),
+                regulation_source: entry.regulation_source.clone(),
+                regulation_section: entry.regulation_section.clone(),
+                section_title: entry.section_title.clone(),
+                similarity: entry.similarity,
+                excerpt: citation_excerpt(&entry.content),
+                content_hash: chunk_content_hash(&entry.content),
+                cited: cited_for.is_some(),
+                applies_to: cited_for.unwrap_or_default(),
+                citation_ref,
+            }
+        })
+        .collect();
+
+    (citations, applies_to.into_keys().collect())
+}
+
+/// Citations stored for a document, flagging chunks changed since generation
+pub async fn load_document_citations(db_pool: &PgPool, document_id: Uuid) -> Result<Vec<StoredCitation>> {
+    let mut citations = sqlx::query_as::<_, StoredCitation>(
+        r#"
+        SELECT
+            c.citation_ref, c.knowledge_entry_id, c.regulation_source, c.regulation_section,
+            c.section_title, c.similarity, c.excerpt, c.content_hash, c.cited, c.applies_to,
+            kb.content AS current_content
+        FROM regulatory_document_citations c
+        LEFT JOIN regulatory_knowledge_base kb ON kb.id = c.knowledge_entry_id
+        WHERE c.document_id = $1
+        ORDER BY LENGTH(c.citation_ref), c.citation_ref
+        "#
+    )
+    .bind(document_id)
+    .fetch_all(db_pool)
+    .await?;
+
+    for citation in &mut citations {
+        citation.source_changed = citation
+            .current_content
+            .as_deref()
+            .map_or(true, |content| chunk_content_hash(content) != citation.content_hash);
+    }
+
+    Ok(citations)
+}
+
 /// Regulatory Document Generator Service
 ///
 /// This service generates regulatory documents using:
@@ -123,7 +261,7 @@ impl RegulatoryDocumentGenerator {
     /// * `Ok(GeneratedDocument)` - Generated and signed document
     pub async fn generate_document(
         &self,
-        request: GenerateDocumentRequest,
+        mut request: GenerateDocumentRequest,
         user_id: Uuid,
     ) -> Result<GeneratedDocument> {
         tracing::info!(
@@ -140,6 +278,17 @@ impl RegulatoryDocumentGenerator {
             tracing::info!("Generated Ed25519 keypair for user {}", user_id);
         }
 
+        // Pin the catalog version in force now; it fills in missing product details
+        let catalog_version = match request.product_ndc.as_deref() {
+            Some(ndc) => Some(self.resolve_catalog_version(ndc).await?),
+            None => None,
+        };
+        if let Some(version) = &catalog_version {
+            let field = |name: &str| version.data.get(name).and_then(|v| v.as_str()).map(str::to_string);
+            request.product_name = request.product_name.take().or_else(|| field("brand_name"));
+            request.manufacturer = request.manufacturer.take().or_else(|| field("labeler_name"));
+        }
+
         // Step 2: Retrieve relevant regulations using RAG (semantic search)
         let rag_context = self
             .retrieve_rag_context(&request.document_type, &request)
@@ -155,6 +304,16 @@ impl RegulatoryDocumentGenerator {
             .generate_document_content(&request, &rag_context, user_id)
             .await?;
 
+        // Tie the document's citations back to the retrieved chunks
+        let (citations, unresolved_citations) = resolve_citations(&rag_context, &content);
+        if !unresolved_citations.is_empty() {
+            tracing::warn!(
+                "Generated {} document cites unknown references: {:?}",
+                request.document_type.as_str(),
+                unresolved_citations
+            );
+        }
+
         // Step 4: Generate document number
         let document_number = self
             .generate_document_number(&request.document_type)
@@ -187,9 +346,12 @@ impl RegulatoryDocumentGenerator {
                 &content_hash_hex,
                 &signature,
                 &rag_context,
+                &unresolved_citations,
                 user_id,
+                catalog_version.as_ref().map(|v| v.id),
             )
             .await?;
+        self.store_citations(document_id, &citations).await?;
 
         // Step 9: Create immutable audit ledger entry
         self.create_ledger_entry(
@@ -229,8 +391,11 @@ impl RegulatoryDocumentGenerator {
                     similarity: entry.similarity,
                 })
                 .collect(),
+            citations,
+            unresolved_citations,
             status: "draft".to_string(),
             generated_by: user_id.to_string(),
+            catalog_version_id: catalog_version.map(|v| v.id),
             created_at: chrono::Utc::now(),
         })
     }
@@ -413,7 +578,7 @@ impl RegulatoryDocumentGenerator {
 
         let messages = vec![ClaudeMessage {
             role: "user".to_string(),
-            content: prompt,
+            content: prompt.into(),
         }];
 
         let config = ClaudeRequestConfig {
@@ -422,45 +587,17 @@ impl RegulatoryDocumentGenerator {
             system_prompt: Some(self.get_document_generation_system_prompt(&request.document_type)),
         };
 
-        // Call Claude API
-        let response = self
+        // Call Claude API; the document JSON is validated (with one repair retry)
+        let validated = self
             .claude_service
-            .send_message(messages, config, user_id, None)
+            .send_message_validated::<serde_json::Value>(messages, config, user_id, None, AiFeature::RegulatoryDocument)
             .await?;
-
-        // Strip markdown code fences if present
-        let json_str = Self::strip_markdown_fences(&response.content);
-
-        // Parse JSON response
-        let content: serde_json::Value = serde_json::from_str(&json_str).map_err(|e| {
-            tracing::error!("Failed to parse document JSON from Claude: {}", e);
-            tracing::debug!("Claude response: {}", response.content);
-            tracing::debug!("After stripping fences: {}", json_str);
-            AppError::Internal(anyhow!("Failed to parse document content: {}", e))
-        })?;
+        let (content, response) = (validated.value, validated.response);
+        self.claude_service.cache_response(&response).await;
 
         Ok(content)
     }
 
-    /// Strip markdown code fences from Claude's response
-    fn strip_markdown_fences(text: &str) -> String {
-        let trimmed = text.trim();
-
-        // Check for ```json ... ``` or ``` ... ```
-        if trimmed.starts_with("```") {
-            let without_start = trimmed.strip_prefix("```json")
-                .or_else(|| trimmed.strip_prefix("```"))
-                .unwrap_or(trimmed);
-
-            let without_end = without_start.strip_suffix("```")
-                .unwrap_or(without_start);
-
-            without_end.trim().to_string()
-        } else {
-            trimmed.to_string()
-        }
-    }
-
     /// Build generation prompt with RAG context
     fn build_generation_prompt(
         &self,
@@ -483,6 +620,9 @@ impl RegulatoryDocumentGenerator {
         if let Some(manufacturer) = &request.manufacturer {
             prompt.push_str(&format!("Manufacturer: {}\n", manufacturer));
         }
+        if let Some(ndc) = &request.product_ndc {
+            prompt.push_str(&format!("NDC: {}\n", ndc));
+        }
         if let Some(test_results) = &request.test_results {
             // Safe: serde_json::to_string_pretty only fails for types with custom serialization that return errors
             // Since we're using serde_json::Value (already validated JSON), this cannot fail
@@ -496,11 +636,11 @@ impl RegulatoryDocumentGenerator {
             prompt.push_str(&format!("\nAdditional Information:\n{}\n", custom_str));
         }
 
-        // Add RAG context (relevant regulations)
+        // Add RAG context (relevant regulations), labelled for citation
         prompt.push_str("\n## Relevant Regulatory Requirements\n");
         for (i, entry) in rag_context.iter().enumerate() {
             prompt.push_str(&format!(
-                "\n### Regulation {} - {} (Similarity: {:.2})\n",
+                "\n### [R{}] {} (Similarity: {:.2})\n",
                 i + 1,
                 entry.section_title,
                 entry.similarity
@@ -511,10 +651,18 @@ impl RegulatoryDocumentGenerator {
             if let Some(section) = &entry.regulation_section {
                 prompt.push_str(&format!("Section: {}\n", section));
             }
-            prompt.push_str(&format!("\n{}\n", &entry.content[..entry.content.len().min(500)]));
+            prompt.push_str(&format!("\n{}\n", citation_excerpt(&entry.content)));
+        }
+        if rag_context.is_empty() {
+            prompt.push_str("\nNo regulatory context is available.\n");
         }
 
         prompt.push_str("\n\nGenerate the document in valid JSON format following the template structure. Ensure all regulatory requirements are addressed.");
+        prompt.push_str(
+            "\n\nCite your sources: add a top-level \"citations\" array with one entry per requirement you drew from \
+            the regulations above, as {\"ref\": \"R1\", \"applies_to\": \"<part of this document>\"}. \
+            Only cite the [R#] references listed above, and do not state regulatory requirements they do not support.",
+        );
 
         prompt
     }
@@ -586,7 +734,9 @@ impl RegulatoryDocumentGenerator {
         content_hash: &str,
         signature: &str,
         rag_context: &[KnowledgeEntry],
+        unresolved_citations: &[String],
         generated_by: Uuid,
+        catalog_version_id: Option<Uuid>,
     ) -> Result<Uuid> {
         // Build RAG context JSON
         let rag_context_json = serde_json::json!({
@@ -595,6 +745,7 @@ impl RegulatoryDocumentGenerator {
                     "id": e.id,
                     "title": e.section_title,
                     "source": e.regulation_source,
+                    "section": e.regulation_section,
                     "similarity": e.similarity
                 })
             }).collect::<Vec<_>>()
@@ -606,9 +757,9 @@ impl RegulatoryDocumentGenerator {
         let doc = sqlx::query!(
             r#"
             INSERT INTO regulatory_documents
-                (document_type, document_number, title, content, content_hash, generated_signature, rag_context, status, generated_by)
+                (document_type, document_number, title, content, content_hash, generated_signature, rag_context, status, generated_by, catalog_version_id, unresolved_citations)
             VALUES
-                ($1, $2, $3, $4, $5, $6, $7, 'draft', $8)
+                ($1, $2, $3, $4, $5, $6, $7, 'draft', $8, $9, $10)
             RETURNING id
             "#,
             document_type.as_str(),
@@ -618,7 +769,9 @@ impl RegulatoryDocumentGenerator {
             content_hash,
             signature,
             rag_context_json,
-            generated_by
+            generated_by,
+            catalog_version_id,
+            serde_json::json!(unresolved_citations)
         )
         .fetch_one(&self.db_pool)
         .await?;
@@ -626,6 +779,44 @@ impl RegulatoryDocumentGenerator {
         Ok(doc.id)
     }
 
+    /// Store the retrieved chunks and their citation status for a document
+    async fn store_citations(&self, document_id: Uuid, citations: &[DocumentCitation]) -> Result<()> {
+        for citation in citations {
+            sqlx::query(
+                r#"
+                INSERT INTO regulatory_document_citations
+                    (document_id, citation_ref, knowledge_entry_id, regulation_source, regulation_section,
+                     section_title, similarity, excerpt, content_hash, cited, applies_to)
+                VALUES
+                    ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                "#
+            )
+            .bind(document_id)
+            .bind(&citation.citation_ref)
+            .bind(citation.knowledge_entry_id)
+            .bind(&citation.regulation_source)
+            .bind(&citation.regulation_section)
+            .bind(&citation.section_title)
+            .bind(citation.similarity)
+            .bind(&citation.excerpt)
+            .bind(&citation.content_hash)
+            .bind(citation.cited)
+            .bind(serde_json::json!(citation.applies_to))
+            .execute(&self.db_pool)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Catalog version of an NDC currently in force
+    async fn resolve_catalog_version(&self, ndc: &str) -> Result<OpenFdaCatalogVersion> {
+        OpenFdaRepository::new(self.db_pool.clone())
+            .find_version_at(ndc.trim(), chrono::Utc::now())
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("NDC {} is not in the OpenFDA catalog", ndc)))
+    }
+
     /// Create immutable audit ledger entry
     async fn create_ledger_entry(
         &self,
@@ -654,3 +845,51 @@ impl RegulatoryDocumentGenerator {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(title: &str, content: &str) -> KnowledgeEntry {
+        KnowledgeEntry {
+            id: Uuid::new_v4(),
+            document_type: "CoA".to_string(),
+            regulation_source: Some("FDA 21 CFR Part 211".to_string()),
+            regulation_section: Some("§211.194".to_string()),
+            section_title: title.to_string(),
+            content: content.to_string(),
+            metadata: serde_json::json!({}),
+            created_at: chrono::Utc::now(),
+            similarity: 0.82,
+        }
+    }
+
+    #[test]
+    fn test_resolve_citations() {
+        let context = vec![entry("Laboratory records", "Records shall include..."), entry("Testing", "Each batch...")];
+        let content = serde_json::json!({
+            "header": {},
+            "citations": [
+                {"ref": "R1", "applies_to": "tests"},
+                {"ref": "[r1]", "applies_to": "conclusion"},
+                {"ref": "R7", "applies_to": "header"}
+            ]
+        });
+
+        let (citations, unresolved) = resolve_citations(&context, &content);
+
+        assert_eq!(citations.len(), 2);
+        assert_eq!(citations[0].citation_ref, "R1");
+        assert!(citations[0].cited);
+        assert_eq!(citations[0].applies_to, vec!["tests", "conclusion"]);
+        assert_eq!(citations[0].content_hash, chunk_content_hash("Records shall include..."));
+        assert!(!citations[1].cited);
+        assert_eq!(unresolved, vec!["R7"]);
+    }
+
+    #[test]
+    fn test_citation_excerpt_is_char_safe() {
+        let content = "é".repeat(CITATION_EXCERPT_CHARS + 10);
+        assert_eq!(citation_excerpt(&content).chars().count(), CITATION_EXCERPT_CHARS);
+    }
+}
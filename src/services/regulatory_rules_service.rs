@@ -0,0 +1,386 @@
+/// Regulatory Rules Service
+///
+/// Evaluates the admin-configured jurisdiction rules (see models::regulatory_rule)
+/// against a trade when an inquiry or transaction is created. The facts come from the
+/// platform: buyer and seller country, the product's DEA schedule (openFDA catalog),
+/// its marketing authorization in the buyer's country (openFDA for the US, EMA for the
+/// EEA, the MHRA / Health Canada / Swissmedic catalogs) and each party's verified
+/// licenses. Blocked trades are refused with the list of unmet requirements; every
+/// evaluation is stored.
+
+use std::collections::HashSet;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::{
+    middleware::error_handling::{AppError, Result},
+    models::regulatory_rule::*,
+};
+
+/// Countries covered by EMA (centralised) marketing authorizations
+const EEA_COUNTRIES: [&str; 30] = [
+    "AT", "BE", "BG", "HR", "CY", "CZ", "DK", "EE", "FI", "FR", "DE", "GR", "HU", "IE", "IT",
+    "LV", "LT", "LU", "MT", "NL", "PL", "PT", "RO", "SK", "SI", "ES", "SE", "IS", "LI", "NO",
+];
+
+/// Countries of the regulator_catalog sources
+const REGULATOR_CATALOG_COUNTRIES: [&str; 3] = ["GB", "CA", "CH"];
+
+#[derive(Debug, sqlx::FromRow)]
+struct ListingRow {
+    seller_id: Uuid,
+    seller_country: Option<String>,
+    brand_name: String,
+    generic_name: String,
+    ndc_code: Option<String>,
+    product_domain: String,
+}
+
+pub struct RegulatoryRulesService {
+    db_pool: PgPool,
+}
+
+impl RegulatoryRulesService {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self { db_pool }
+    }
+
+    // ========================================================================
+    // RULES (admin)
+    // ========================================================================
+
+    pub async fn list_rules(&self, include_inactive: bool) -> Result<Vec<RegulatoryRule>> {
+        let rules = sqlx::query_as::<_, RegulatoryRule>(
+            "SELECT * FROM regulatory_rules WHERE is_active OR $1 ORDER BY is_active DESC, name"
+        )
+        .bind(include_inactive)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(rules)
+    }
+
+    pub async fn get_rule(&self, rule_id: Uuid) -> Result<RegulatoryRule> {
+        sqlx::query_as::<_, RegulatoryRule>("SELECT * FROM regulatory_rules WHERE id = $1")
+            .bind(rule_id)
+            .fetch_optional(&self.db_pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Rule not found".to_string()))
+    }
+
+    pub async fn create_rule(&self, request: RegulatoryRuleRequest, admin_id: Uuid) -> Result<RegulatoryRule> {
+        let request = request.normalize().map_err(AppError::BadRequest)?;
+
+        let rule = sqlx::query_as::<_, RegulatoryRule>(
+            r#"
+            INSERT INTO regulatory_rules (
+                name, description, seller_countries, buyer_countries, scope, product_domains,
+                dea_schedules, requirement_type, license_type, enforcement, guidance, is_active,
+                created_by, updated_by
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $13)
+            RETURNING *
+            "#
+        )
+        .bind(&request.name)
+        .bind(&request.description)
+        .bind(&request.seller_countries)
+        .bind(&request.buyer_countries)
+        .bind(request.scope.unwrap_or(RuleScope::Any).as_str())
+        .bind(&request.product_domains)
+        .bind(&request.dea_schedules)
+        .bind(request.requirement_type.as_str())
+        .bind(request.license_type.map(|t| t.as_str()))
+        .bind(request.enforcement.as_str())
+        .bind(&request.guidance)
+        .bind(request.is_active.unwrap_or(true))
+        .bind(admin_id)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        Ok(rule)
+    }
+
+    pub async fn update_rule(&self, rule_id: Uuid, request: RegulatoryRuleRequest, admin_id: Uuid) -> Result<RegulatoryRule> {
+        let request = request.normalize().map_err(AppError::BadRequest)?;
+
+        sqlx::query_as::<_, RegulatoryRule>(
+            r#"
+            UPDATE regulatory_rules
+            SET name = $2, description = $3, seller_countries = $4, buyer_countries = $5,
+                scope = $6, product_domains = $7, dea_schedules = $8, requirement_type = $9,
+                license_type = $10, enforcement = $11, guidance = $12, is_active = $13,
+                updated_by = $14, updated_at = NOW()
+            WHERE id = $1
+            RETURNING *
+            "#
+        )
+        .bind(rule_id)
+        .bind(&request.name)
+        .bind(&request.description)
+        .bind(&request.seller_countries)
+        .bind(&request.buyer_countries)
+        .bind(request.scope.unwrap_or(RuleScope::Any).as_str())
+        .bind(&request.product_domains)
+        .bind(&request.dea_schedules)
+        .bind(request.requirement_type.as_str())
+        .bind(request.license_type.map(|t| t.as_str()))
+        .bind(request.enforcement.as_str())
+        .bind(&request.guidance)
+        .bind(request.is_active.unwrap_or(true))
+        .bind(admin_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Rule not found".to_string()))
+    }
+
+    /// Stored evaluations keep the rule's name and result
+    pub async fn delete_rule(&self, rule_id: Uuid) -> Result<()> {
+        let result = sqlx::query("DELETE FROM regulatory_rules WHERE id = $1")
+            .bind(rule_id)
+            .execute(&self.db_pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Rule not found".to_string()));
+        }
+        Ok(())
+    }
+
+    // ========================================================================
+    // EVALUATION
+    // ========================================================================
+
+    /// Requirements for `buyer_id` buying the listing
+    pub async fn evaluate(&self, inventory_id: Uuid, buyer_id: Uuid) -> Result<RegulatoryEvaluation> {
+        let rules = self.list_rules(false).await?;
+        let trade = self.trade_facts(inventory_id, buyer_id).await?;
+        Ok(evaluate_rules(&rules, &trade))
+    }
+
+    /// Evaluate a trade about to be created. Blocked trades are recorded and refused
+    /// with the unmet requirements; otherwise the caller records the evaluation with
+    /// the created inquiry or transaction.
+    pub async fn enforce(
+        &self,
+        context: EvaluationContext,
+        inventory_id: Uuid,
+        buyer_id: Uuid,
+    ) -> Result<RegulatoryEvaluation> {
+        let evaluation = self.evaluate(inventory_id, buyer_id).await?;
+
+        if evaluation.blocked {
+            self.record(context, &evaluation, None, None).await?;
+            tracing::info!(
+                "Regulatory rules refused {} of inventory {} by buyer {}",
+                context.as_str(),
+                inventory_id,
+                buyer_id
+            );
+            return Err(AppError::Forbidden(evaluation.blocking_summary()));
+        }
+
+        Ok(evaluation)
+    }
+
+    pub async fn record(
+        &self,
+        context: EvaluationContext,
+        evaluation: &RegulatoryEvaluation,
+        inquiry_id: Option<Uuid>,
+        transaction_id: Option<Uuid>,
+    ) -> Result<RegulatoryRuleEvaluation> {
+        let record = sqlx::query_as::<_, RegulatoryRuleEvaluation>(
+            r#"
+            INSERT INTO regulatory_rule_evaluations (
+                context, inquiry_id, transaction_id, inventory_id, buyer_id, seller_id,
+                buyer_country, seller_country, blocked, requirements
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            RETURNING *
+            "#
+        )
+        .bind(context.as_str())
+        .bind(inquiry_id)
+        .bind(transaction_id)
+        .bind(evaluation.inventory_id)
+        .bind(evaluation.buyer_id)
+        .bind(evaluation.seller_id)
+        .bind(&evaluation.buyer_country)
+        .bind(&evaluation.seller_country)
+        .bind(evaluation.blocked)
+        .bind(serde_json::to_value(&evaluation.requirements).unwrap_or_default())
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        Ok(record)
+    }
+
+    /// Latest evaluation of an inquiry (or its transaction), for its buyer or seller
+    pub async fn latest_for_inquiry(&self, inquiry_id: Uuid, user_id: Uuid) -> Result<RegulatoryRuleEvaluation> {
+        sqlx::query_as::<_, RegulatoryRuleEvaluation>(
+            r#"
+            SELECT * FROM regulatory_rule_evaluations
+            WHERE inquiry_id = $1 AND (buyer_id = $2 OR seller_id = $2)
+            ORDER BY evaluated_at DESC
+            LIMIT 1
+            "#
+        )
+        .bind(inquiry_id)
+        .bind(user_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("No regulatory evaluation for this inquiry".to_string()))
+    }
+
+    pub async fn list_evaluations(&self, query: &RegulatoryEvaluationQuery) -> Result<Vec<RegulatoryRuleEvaluation>> {
+        let limit = query.limit.unwrap_or(50).clamp(1, 200);
+        let offset = query.offset.unwrap_or(0).max(0);
+
+        let evaluations = sqlx::query_as::<_, RegulatoryRuleEvaluation>(
+            r#"
+            SELECT * FROM regulatory_rule_evaluations
+            WHERE ($1::BOOLEAN IS NULL OR blocked = $1)
+              AND ($2::UUID IS NULL OR buyer_id = $2 OR seller_id = $2)
+            ORDER BY evaluated_at DESC
+            LIMIT $3 OFFSET $4
+            "#
+        )
+        .bind(query.blocked)
+        .bind(query.user_id)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(evaluations)
+    }
+
+    // ========================================================================
+    // FACTS
+    // ========================================================================
+
+    async fn trade_facts(&self, inventory_id: Uuid, buyer_id: Uuid) -> Result<TradeFacts> {
+        let listing = sqlx::query_as::<_, ListingRow>(
+            r#"
+            SELECT i.user_id AS seller_id, s.country_code AS seller_country,
+                   p.brand_name, p.generic_name, p.ndc_code, p.product_domain
+            FROM inventory i
+            JOIN pharmaceuticals p ON p.id = i.pharmaceutical_id
+            JOIN users s ON s.id = i.user_id
+            WHERE i.id = $1
+            "#
+        )
+        .bind(inventory_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Inventory not found".to_string()))?;
+
+        let buyer_country: Option<String> = sqlx::query_scalar("SELECT country_code FROM users WHERE id = $1")
+            .bind(buyer_id)
+            .fetch_optional(&self.db_pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+        let dea_schedule = match listing.ndc_code.as_deref() {
+            Some(ndc) => sqlx::query_scalar::<_, Option<String>>(
+                "SELECT dea_schedule FROM openfda_catalog WHERE product_ndc = $1"
+            )
+            .bind(ndc)
+            .fetch_optional(&self.db_pool)
+            .await?
+            .flatten()
+            .map(|s| s.trim().to_ascii_uppercase())
+            .filter(|s| !s.is_empty()),
+            None => None,
+        };
+
+        let authorized_in_buyer_country = match buyer_country.as_deref() {
+            Some(country) => self.authorized_in(&listing, country).await?,
+            None => None,
+        };
+
+        Ok(TradeFacts {
+            inventory_id,
+            buyer_id,
+            seller_id: listing.seller_id,
+            buyer_country,
+            seller_country: listing.seller_country.clone(),
+            product_name: listing.brand_name.clone(),
+            product_domain: listing.product_domain.clone(),
+            dea_schedule,
+            authorized_in_buyer_country,
+            buyer_licenses: self.valid_license_types(buyer_id).await?,
+            seller_licenses: self.valid_license_types(listing.seller_id).await?,
+        })
+    }
+
+    /// Whether a regulator catalog lists the product as authorized in `country`;
+    /// None when no catalog covers the country
+    async fn authorized_in(&self, listing: &ListingRow, country: &str) -> Result<Option<bool>> {
+        let authorized: bool = if country == "US" {
+            sqlx::query_scalar(
+                r#"
+                SELECT EXISTS (
+                    SELECT 1 FROM openfda_catalog
+                    WHERE (product_ndc = $1 OR ($1 IS NULL AND brand_name ILIKE $2))
+                      AND (listing_expiration_date IS NULL OR listing_expiration_date >= CURRENT_DATE)
+                )
+                "#
+            )
+            .bind(&listing.ndc_code)
+            .bind(&listing.brand_name)
+            .fetch_one(&self.db_pool)
+            .await?
+        } else if EEA_COUNTRIES.contains(&country) {
+            sqlx::query_scalar(
+                r#"
+                SELECT EXISTS (
+                    SELECT 1 FROM ema_catalog
+                    WHERE (product_name ILIKE $1 OR inn_name ILIKE $2)
+                      AND authorization_status ILIKE 'authori%'
+                )
+                "#
+            )
+            .bind(&listing.brand_name)
+            .bind(&listing.generic_name)
+            .fetch_one(&self.db_pool)
+            .await?
+        } else if REGULATOR_CATALOG_COUNTRIES.contains(&country) {
+            sqlx::query_scalar(
+                r#"
+                SELECT EXISTS (
+                    SELECT 1 FROM regulator_catalog
+                    WHERE country = $1
+                      AND (product_name ILIKE $2 OR LOWER($3) = ANY(SELECT LOWER(a) FROM UNNEST(active_ingredients) a))
+                      AND COALESCE(authorization_status, '') !~* '(withdrawn|cancel|suspend|revoked|expired)'
+                )
+                "#
+            )
+            .bind(country)
+            .bind(&listing.brand_name)
+            .bind(&listing.generic_name)
+            .fetch_one(&self.db_pool)
+            .await?
+        } else {
+            return Ok(None);
+        };
+
+        Ok(Some(authorized))
+    }
+
+    async fn valid_license_types(&self, user_id: Uuid) -> Result<HashSet<String>> {
+        let types: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT DISTINCT license_type FROM business_licenses
+            WHERE user_id = $1
+              AND status = 'verified'
+              AND (expires_on IS NULL OR expires_on > CURRENT_DATE)
+            "#
+        )
+        .bind(user_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(types.into_iter().collect())
+    }
+}
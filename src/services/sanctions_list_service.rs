@@ -0,0 +1,265 @@
+/// Sanctions List Service
+///
+/// Downloads the OFAC SDN list and the EU consolidated financial sanctions list into
+/// `sanctions_list_entries`, which sanctions screening matches names against. Each
+/// sync upserts the downloaded entries (entry ids, and admins' false-positive
+/// clearances, survive a sync) and removes entries no longer listed. A download that
+/// is empty or less than half the size of the stored list is rejected instead of
+/// wiping the list.
+///
+/// Configuration:
+/// - `OFAC_SDN_BASE_URL` (default https://www.treasury.gov/ofac/downloads): directory
+///   holding sdn.csv, alt.csv and add.csv
+/// - `EU_SANCTIONS_LIST_URL`: download URL of the EU consolidated list CSV (v1.1),
+///   including its access token; the EU list is not synced without it
+
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Postgres, QueryBuilder};
+use uuid::Uuid;
+use crate::{
+    middleware::error_handling::{AppError, Result},
+    models::sanctions::*,
+    services::{regulator_catalogs::SourceClient, Shutdown, SyncLogTable},
+};
+
+/// Entries per upsert statement
+const UPSERT_BATCH_SIZE: usize = 500;
+
+pub struct SanctionsListService {
+    db_pool: PgPool,
+}
+
+impl SanctionsListService {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self { db_pool }
+    }
+
+    pub fn is_configured(source: SanctionsSource) -> bool {
+        match source {
+            SanctionsSource::OfacSdn => true,
+            SanctionsSource::EuConsolidated => eu_list_url().is_some(),
+        }
+    }
+
+    /// Entry count and latest sync of every list
+    pub async fn status(&self) -> Result<Vec<SanctionsListStatus>> {
+        let mut lists = Vec::with_capacity(SanctionsSource::ALL.len());
+
+        for source in SanctionsSource::ALL {
+            let entry_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sanctions_list_entries WHERE source = $1")
+                .bind(source.as_str())
+                .fetch_one(&self.db_pool)
+                .await?;
+
+            let last_sync = sqlx::query_as::<_, SanctionsListSync>(
+                "SELECT * FROM sanctions_list_syncs WHERE source = $1 ORDER BY started_at DESC LIMIT 1"
+            )
+            .bind(source.as_str())
+            .fetch_optional(&self.db_pool)
+            .await?;
+
+            lists.push(SanctionsListStatus {
+                source,
+                label: source.label(),
+                configured: Self::is_configured(source),
+                entry_count,
+                last_sync,
+            });
+        }
+
+        Ok(lists)
+    }
+
+    /// Time of the latest completed sync of any list; None while no list was ever synced
+    pub async fn latest_completed_sync(&self) -> Result<Option<DateTime<Utc>>> {
+        let synced_at: Option<DateTime<Utc>> = sqlx::query_scalar(
+            "SELECT MAX(completed_at) FROM sanctions_list_syncs WHERE status = 'completed'"
+        )
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        Ok(synced_at)
+    }
+
+    /// Sync every configured list; a failing list doesn't stop the others.
+    /// Returns the number of entries stored.
+    pub async fn sync_all(&self, started_by: Option<Uuid>) -> Result<i64> {
+        let mut stored = 0;
+        let mut last_error = None;
+
+        for source in SanctionsSource::ALL.into_iter().filter(|s| Self::is_configured(*s)) {
+            match self.sync(source, started_by).await {
+                Ok(sync) => stored += sync.entry_count.unwrap_or(0) as i64,
+                Err(AppError::Conflict) => {
+                    tracing::info!("{} sync skipped: another sync is in progress", source.label());
+                }
+                Err(e) => {
+                    tracing::error!("{} sync failed: {}", source.label(), e);
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        match last_error {
+            Some(e) if stored == 0 => Err(e),
+            _ => Ok(stored),
+        }
+    }
+
+    /// Download and store one list
+    pub async fn sync(&self, source: SanctionsSource, started_by: Option<Uuid>) -> Result<SanctionsListSync> {
+        if !Self::is_configured(source) {
+            return Err(AppError::BadRequest(format!("{} is not configured", source.label())));
+        }
+
+        let sync_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO sanctions_list_syncs (source, started_by) VALUES ($1, $2) RETURNING id"
+        )
+        .bind(source.as_str())
+        .bind(started_by)
+        .fetch_one(&self.db_pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db_err) if db_err.code().as_deref() == Some("23505") => AppError::Conflict,
+            other => other.into(),
+        })?;
+        let _in_flight = Shutdown::track_sync(SyncLogTable::SanctionsList, sync_id);
+
+        let result = match self.download(source).await {
+            Ok(entries) => self.store(source, &entries).await,
+            Err(e) => Err(e),
+        };
+
+        let (status, entry_count, error_message) = match &result {
+            Ok(count) => ("completed", Some(*count as i32), None),
+            Err(e) if Shutdown::requested() => ("interrupted", None, Some(e.to_string())),
+            Err(e) => ("failed", None, Some(e.to_string())),
+        };
+
+        let sync = sqlx::query_as::<_, SanctionsListSync>(
+            r#"
+            UPDATE sanctions_list_syncs
+            SET status = $2, entry_count = $3, error_message = $4, completed_at = NOW()
+            WHERE id = $1
+            RETURNING *
+            "#
+        )
+        .bind(sync_id)
+        .bind(status)
+        .bind(entry_count)
+        .bind(error_message)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        result?;
+        tracing::info!("{} synced: {} entries", source.label(), sync.entry_count.unwrap_or(0));
+        Ok(sync)
+    }
+
+    pub async fn list_syncs(&self, limit: i64) -> Result<Vec<SanctionsListSync>> {
+        let syncs = sqlx::query_as::<_, SanctionsListSync>(
+            "SELECT * FROM sanctions_list_syncs ORDER BY started_at DESC LIMIT $1"
+        )
+        .bind(limit.clamp(1, 200))
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(syncs)
+    }
+
+    async fn download(&self, source: SanctionsSource) -> Result<Vec<ParsedSanctionsEntry>> {
+        let client = SourceClient::new();
+
+        let parsed = match source {
+            SanctionsSource::OfacSdn => {
+                let base_url = std::env::var("OFAC_SDN_BASE_URL")
+                    .unwrap_or_else(|_| "https://www.treasury.gov/ofac/downloads".to_string());
+                let base_url = base_url.trim_end_matches('/');
+
+                let sdn = client.get_bytes(&format!("{}/sdn.csv", base_url), &[]).await?;
+                let alt = client.get_bytes(&format!("{}/alt.csv", base_url), &[]).await?;
+                let add = client.get_bytes(&format!("{}/add.csv", base_url), &[]).await?;
+                parse_ofac_sdn(&sdn, &alt, &add)
+            }
+            SanctionsSource::EuConsolidated => {
+                let url = eu_list_url().ok_or_else(|| {
+                    AppError::BadRequest("EU_SANCTIONS_LIST_URL is not configured".to_string())
+                })?;
+                let data = client.get_bytes(&url, &[]).await?;
+                parse_eu_consolidated(&data)
+            }
+        };
+
+        parsed.map_err(|e| AppError::Internal(anyhow::anyhow!("{}: {}", source.label(), e)))
+    }
+
+    /// Upsert the downloaded entries and drop the ones no longer listed
+    async fn store(&self, source: SanctionsSource, entries: &[ParsedSanctionsEntry]) -> Result<usize> {
+        let stored_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sanctions_list_entries WHERE source = $1")
+            .bind(source.as_str())
+            .fetch_one(&self.db_pool)
+            .await?;
+
+        if entries.is_empty() || (entries.len() as i64) * 2 < stored_count {
+            return Err(AppError::Internal(anyhow::anyhow!(
+                "{} download has {} entries (stored: {}); keeping the stored list",
+                source.label(),
+                entries.len(),
+                stored_count
+            )));
+        }
+
+        let synced_at = Utc::now();
+        let mut tx = self.db_pool.begin().await?;
+
+        for batch in entries.chunks(UPSERT_BATCH_SIZE) {
+            let mut builder = QueryBuilder::<Postgres>::new(
+                "INSERT INTO sanctions_list_entries (source, external_id, entity_type, primary_name, names, name_keys, programs, countries, remarks, synced_at) ",
+            );
+            builder.push_values(batch, |mut row, entry| {
+                row.push_bind(source.as_str())
+                    .push_bind(&entry.external_id)
+                    .push_bind(entry.entity_type)
+                    .push_bind(&entry.primary_name)
+                    .push_bind(entry.normalized_names())
+                    .push_bind(entry.name_keys())
+                    .push_bind(&entry.programs)
+                    .push_bind(&entry.countries)
+                    .push_bind(&entry.remarks)
+                    .push_bind(synced_at);
+            });
+            builder.push(
+                r#"
+                ON CONFLICT (source, external_id) DO UPDATE SET
+                    entity_type = EXCLUDED.entity_type,
+                    primary_name = EXCLUDED.primary_name,
+                    names = EXCLUDED.names,
+                    name_keys = EXCLUDED.name_keys,
+                    programs = EXCLUDED.programs,
+                    countries = EXCLUDED.countries,
+                    remarks = EXCLUDED.remarks,
+                    synced_at = EXCLUDED.synced_at
+                "#,
+            );
+            builder.build().execute(&mut *tx).await?;
+        }
+
+        let removed = sqlx::query("DELETE FROM sanctions_list_entries WHERE source = $1 AND synced_at < $2")
+            .bind(source.as_str())
+            .bind(synced_at)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+        tx.commit().await?;
+
+        if removed > 0 {
+            tracing::info!("{}: {} entries delisted", source.label(), removed);
+        }
+        Ok(entries.len())
+    }
+}
+
+fn eu_list_url() -> Option<String> {
+    std::env::var("EU_SANCTIONS_LIST_URL").ok().filter(|u| !u.trim().is_empty())
+}
@@ -0,0 +1,386 @@
+/// Sanctions Screening Service
+///
+/// Screens a user's company name and contact person against the synced OFAC SDN and
+/// EU consolidated lists (see SanctionsListService) at registration and, for both
+/// parties, before a transaction completes. Every screening is stored with its match
+/// scores.
+///
+/// - No match above the review score: clear
+/// - Potential match: the transaction is flagged and proceeds; admins are notified
+/// - Strong match, or a user with a confirmed match: the transaction is blocked and
+///   can't complete until an admin clears the match as a false positive
+///
+/// Admins review pending screenings. A false positive clears the matched entries for
+/// that user for good; a confirmed match blocks the user from completing transactions.
+
+use std::collections::HashSet;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::{
+    middleware::error_handling::{AppError, Result},
+    models::{alerts::AlertPayload, sanctions::*},
+    repositories::UserRepository,
+    services::{NotificationService, SanctionsListService},
+};
+
+/// Candidate list entries scored per screened name
+const MAX_CANDIDATES: i64 = 1000;
+
+pub struct SanctionsScreeningService {
+    db_pool: PgPool,
+    user_repo: UserRepository,
+    thresholds: ScreeningThresholds,
+}
+
+impl SanctionsScreeningService {
+    pub fn new(db_pool: PgPool, encryption_key: &str) -> Result<Self> {
+        let user_repo = UserRepository::new(db_pool.clone(), encryption_key)?;
+        Ok(Self {
+            db_pool,
+            user_repo,
+            thresholds: ScreeningThresholds::from_env(),
+        })
+    }
+
+    /// Screen a user's company name and contact person and store the result
+    pub async fn screen_user(
+        &self,
+        user_id: Uuid,
+        trigger: ScreeningTrigger,
+        transaction_id: Option<Uuid>,
+    ) -> Result<SanctionsScreening> {
+        let user = self.user_repo
+            .find_by_id(user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+        let (country_code, blocked): (Option<String>, bool) = sqlx::query_as(
+            "SELECT country_code, sanctions_blocked_at IS NOT NULL FROM users WHERE id = $1"
+        )
+        .bind(user_id)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        let mut screened_names: Vec<String> = Vec::new();
+        for name in [&user.company_name, &user.contact_person] {
+            let name = name.trim().to_string();
+            if !name.is_empty() && !screened_names.contains(&name) {
+                screened_names.push(name);
+            }
+        }
+
+        let cleared: HashSet<Uuid> = sqlx::query_scalar::<_, Uuid>(
+            "SELECT entry_id FROM sanctions_cleared_matches WHERE user_id = $1"
+        )
+        .bind(user_id)
+        .fetch_all(&self.db_pool)
+        .await?
+        .into_iter()
+        .collect();
+
+        let mut matches = Vec::new();
+        for screened_name in &screened_names {
+            matches.extend(self.match_name(screened_name, &cleared).await?);
+        }
+        let matches = rank_matches(matches);
+
+        let top_score = matches.first().map_or(0.0, |m| m.score);
+        let outcome = if blocked {
+            ScreeningOutcome::Blocked
+        } else {
+            self.thresholds.outcome(top_score)
+        };
+        let status = if matches.is_empty() { "clear" } else { "pending_review" };
+
+        let lists_synced_at = SanctionsListService::new(self.db_pool.clone()).latest_completed_sync().await?;
+        if lists_synced_at.is_none() {
+            tracing::warn!("Sanctions screening of user {} ran without a synced sanctions list", user_id);
+        }
+
+        let screening = sqlx::query_as::<_, SanctionsScreening>(
+            r#"
+            INSERT INTO sanctions_screenings (
+                user_id, transaction_id, trigger, screened_names, country_code,
+                top_score, outcome, status, matches, lists_synced_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            RETURNING *
+            "#
+        )
+        .bind(user_id)
+        .bind(transaction_id)
+        .bind(trigger.as_str())
+        .bind(&screened_names)
+        .bind(&country_code)
+        .bind(top_score)
+        .bind(outcome.as_str())
+        .bind(status)
+        .bind(serde_json::to_value(&matches).unwrap_or_default())
+        .bind(lists_synced_at)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        if screening.is_pending_review() {
+            tracing::warn!(
+                "Sanctions screening {} of user {}: {} potential match(es), top score {:.2}, {}",
+                screening.id,
+                user_id,
+                matches.len(),
+                top_score,
+                outcome.as_str()
+            );
+            self.notify_admins(&screening, &user.company_name).await;
+        }
+
+        Ok(screening)
+    }
+
+    /// Screen both parties before the seller completes a pending transaction. A blocked
+    /// outcome refuses completion; the transaction stays pending until an admin clears
+    /// the match.
+    pub async fn screen_transaction(&self, transaction_id: Uuid, seller_user_id: Uuid) -> Result<ScreeningOutcome> {
+        let (seller_id, buyer_id, status): (Uuid, Uuid, String) = sqlx::query_as(
+            "SELECT seller_id, buyer_id, status FROM transactions WHERE id = $1"
+        )
+        .bind(transaction_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Resource not found".to_string()))?;
+
+        if seller_id != seller_user_id {
+            return Err(AppError::Forbidden("Access denied".to_string()));
+        }
+        if status != "pending" {
+            return Err(AppError::InvalidInput("Transaction is not pending".to_string()));
+        }
+
+        let mut outcome = ScreeningOutcome::Clear;
+        for party in [seller_id, buyer_id] {
+            let screening = self.screen_user(party, ScreeningTrigger::Transaction, Some(transaction_id)).await?;
+            let party_outcome = match screening.outcome.as_str() {
+                "blocked" => ScreeningOutcome::Blocked,
+                "flagged" => ScreeningOutcome::Flagged,
+                _ => ScreeningOutcome::Clear,
+            };
+            outcome = outcome.max(party_outcome);
+        }
+
+        sqlx::query("UPDATE transactions SET sanctions_status = $2 WHERE id = $1")
+            .bind(transaction_id)
+            .bind(outcome.as_str())
+            .execute(&self.db_pool)
+            .await?;
+
+        if outcome == ScreeningOutcome::Blocked {
+            tracing::warn!("Transaction {} held for sanctions review", transaction_id);
+            return Err(AppError::Forbidden(
+                "This transaction is on hold pending a sanctions compliance review".to_string(),
+            ));
+        }
+
+        Ok(outcome)
+    }
+
+    // ========================================================================
+    // ADMIN REVIEW
+    // ========================================================================
+
+    pub async fn list(&self, query: &ScreeningQuery) -> Result<Vec<SanctionsScreening>> {
+        let status = query.status.as_deref().unwrap_or("pending_review");
+        let status = match status {
+            "all" => None,
+            "clear" | "pending_review" | "false_positive" | "confirmed_match" => Some(status),
+            other => return Err(AppError::InvalidInput(format!("Unknown status '{}'", other))),
+        };
+        let limit = query.limit.unwrap_or(50).clamp(1, 200);
+        let offset = query.offset.unwrap_or(0).max(0);
+
+        let screenings = sqlx::query_as::<_, SanctionsScreening>(
+            r#"
+            SELECT * FROM sanctions_screenings
+            WHERE ($1::TEXT IS NULL OR status = $1)
+              AND ($2::UUID IS NULL OR user_id = $2)
+              AND ($3::UUID IS NULL OR transaction_id = $3)
+            ORDER BY top_score DESC, created_at DESC
+            LIMIT $4 OFFSET $5
+            "#
+        )
+        .bind(status)
+        .bind(query.user_id)
+        .bind(query.transaction_id)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(screenings)
+    }
+
+    pub async fn get(&self, screening_id: Uuid) -> Result<SanctionsScreening> {
+        sqlx::query_as::<_, SanctionsScreening>("SELECT * FROM sanctions_screenings WHERE id = $1")
+            .bind(screening_id)
+            .fetch_optional(&self.db_pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Screening not found".to_string()))
+    }
+
+    /// Clear a pending screening as a false positive, or confirm the match
+    pub async fn review(
+        &self,
+        screening_id: Uuid,
+        admin_id: Uuid,
+        request: ReviewScreeningRequest,
+    ) -> Result<SanctionsScreening> {
+        let mut tx = self.db_pool.begin().await?;
+
+        let screening = sqlx::query_as::<_, SanctionsScreening>(
+            r#"
+            UPDATE sanctions_screenings
+            SET status = $2, reviewed_by = $3, reviewed_at = NOW(), review_notes = $4
+            WHERE id = $1 AND status = 'pending_review'
+            RETURNING *
+            "#
+        )
+        .bind(screening_id)
+        .bind(request.decision.as_str())
+        .bind(admin_id)
+        .bind(request.notes.trim())
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(screening) = screening else {
+            let existing = self.get(screening_id).await?;
+            return Err(AppError::BadRequest(format!("The screening is {}", existing.status)));
+        };
+
+        match request.decision {
+            ScreeningReviewDecision::FalsePositive => {
+                for m in screening.parsed_matches() {
+                    sqlx::query(
+                        r#"
+                        INSERT INTO sanctions_cleared_matches (user_id, entry_id, screening_id, cleared_by)
+                        SELECT $1, $2, $3, $4
+                        WHERE EXISTS (SELECT 1 FROM sanctions_list_entries WHERE id = $2)
+                        ON CONFLICT (user_id, entry_id) DO NOTHING
+                        "#
+                    )
+                    .bind(screening.user_id)
+                    .bind(m.entry_id)
+                    .bind(screening.id)
+                    .bind(admin_id)
+                    .execute(&mut *tx)
+                    .await?;
+                }
+
+                // Release the transaction once none of its screenings awaits review
+                if let Some(transaction_id) = screening.transaction_id {
+                    sqlx::query(
+                        r#"
+                        UPDATE transactions SET sanctions_status = 'clear'
+                        WHERE id = $1
+                          AND sanctions_status IN ('flagged', 'blocked')
+                          AND NOT EXISTS (
+                              SELECT 1 FROM sanctions_screenings s
+                              JOIN users u ON u.id = s.user_id
+                              WHERE s.transaction_id = $1
+                                AND (s.status IN ('pending_review', 'confirmed_match') OR u.sanctions_blocked_at IS NOT NULL)
+                          )
+                        "#
+                    )
+                    .bind(transaction_id)
+                    .execute(&mut *tx)
+                    .await?;
+                }
+            }
+            ScreeningReviewDecision::ConfirmedMatch => {
+                sqlx::query("UPDATE users SET sanctions_blocked_at = COALESCE(sanctions_blocked_at, NOW()) WHERE id = $1")
+                    .bind(screening.user_id)
+                    .execute(&mut *tx)
+                    .await?;
+
+                if let Some(transaction_id) = screening.transaction_id {
+                    sqlx::query("UPDATE transactions SET sanctions_status = 'blocked' WHERE id = $1")
+                        .bind(transaction_id)
+                        .execute(&mut *tx)
+                        .await?;
+                }
+            }
+        }
+
+        tx.commit().await?;
+        Ok(screening)
+    }
+
+    // ========================================================================
+    // MATCHING
+    // ========================================================================
+
+    /// List entries whose names are similar to `screened_name`, cleared entries excluded
+    async fn match_name(&self, screened_name: &str, cleared: &HashSet<Uuid>) -> Result<Vec<ScreeningMatch>> {
+        let normalized = normalize_name(screened_name);
+        let keys = name_keys(&normalized);
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let candidates = sqlx::query_as::<_, SanctionsListEntry>(
+            "SELECT * FROM sanctions_list_entries WHERE name_keys && $1 LIMIT $2"
+        )
+        .bind(&keys)
+        .bind(MAX_CANDIDATES)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let matches = candidates
+            .into_iter()
+            .filter(|entry| !cleared.contains(&entry.id))
+            .filter_map(|entry| {
+                let (matched_name, score) = best_entry_score(&normalized, &entry.names)?;
+                (score >= self.thresholds.review_score).then(|| ScreeningMatch {
+                    entry_id: entry.id,
+                    source: entry.source,
+                    external_id: entry.external_id,
+                    primary_name: entry.primary_name,
+                    matched_name,
+                    screened_name: screened_name.to_string(),
+                    score,
+                    programs: entry.programs,
+                })
+            })
+            .collect();
+
+        Ok(matches)
+    }
+
+    /// Notifications are best effort; the screening is already stored
+    async fn notify_admins(&self, screening: &SanctionsScreening, company_name: &str) {
+        let admin_ids: Vec<Uuid> = match sqlx::query_scalar(
+            "SELECT id FROM users WHERE role IN ('admin', 'superadmin')"
+        )
+        .fetch_all(&self.db_pool)
+        .await
+        {
+            Ok(ids) => ids,
+            Err(e) => {
+                tracing::warn!("Could not load admins for sanctions screening {}: {}", screening.id, e);
+                return;
+            }
+        };
+
+        let notification_service = NotificationService::new(self.db_pool.clone());
+        for admin_id in admin_ids {
+            let payload = AlertPayload::new_sanctions_hit(
+                admin_id,
+                screening.id,
+                screening.user_id,
+                company_name,
+                screening.top_score,
+                &screening.outcome,
+                screening.transaction_id,
+            );
+            if let Err(e) = notification_service.create_alert(payload).await {
+                tracing::warn!("Could not notify admin {} about sanctions screening {}: {:?}", admin_id, screening.id, e);
+            }
+        }
+    }
+}
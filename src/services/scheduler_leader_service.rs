@@ -0,0 +1,284 @@
+/// Scheduler Leader Service - leader election for background schedulers
+///
+/// Schedulers whose runs must happen once across all replicas (catalog syncs, ERP and
+/// EDI polling, expiry sweeps, billing statements, anomaly scans, ...) only run on the
+/// instance leading them. Leadership is a Postgres session advisory lock per scheduler,
+/// all held on one dedicated connection of the instance:
+/// - `SchedulerLeader::leads` is checked on every tick and takes the lock when no other
+///   instance holds it, so a replica takes over within a tick of the leader going away
+/// - a heartbeat every 15 seconds pings that connection and records it in
+///   `scheduler_leaders`; if the connection fails, Postgres has released its locks and
+///   the instance stops leading until it wins them again
+/// - a leader whose heartbeat is older than SCHEDULER_LEADER_STALE_SECONDS (default 60)
+///   while its lock is still held (a hung process, a partition Postgres hasn't noticed)
+///   has its session terminated by the next contender
+/// - on shutdown the locks are released so another instance takes over right away
+///
+/// A run in progress when leadership moves still finishes; the catalog and ERP syncs
+/// also refuse to start while one of their runs is in progress.
+///
+/// Schedulers that claim their work row by row (the alert checks, background jobs,
+/// webhook deliveries, NL report schedules, export purges, search indexing) and the
+/// per-instance ones (cache flushes, session revocations, rule reloads) run on every
+/// instance and don't go through leadership.
+
+use once_cell::sync::{Lazy, OnceCell};
+use sqlx::{Connection, PgConnection, PgPool};
+use std::collections::BTreeSet;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+use crate::middleware::{error_handling::Result, metrics};
+
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+const DEFAULT_STALE_SECONDS: u64 = 60;
+
+static POOL: OnceCell<PgPool> = OnceCell::new();
+static SESSION: Lazy<Mutex<LeaderSession>> = Lazy::new(|| Mutex::new(LeaderSession::default()));
+
+static INSTANCE_ID: Lazy<String> = Lazy::new(|| {
+    format!(
+        "{}-{}-{}",
+        std::env::var("HOSTNAME").unwrap_or_else(|_| "atlas".to_string()),
+        std::process::id(),
+        &Uuid::new_v4().simple().to_string()[..8]
+    )
+});
+
+static STALE_SECONDS: Lazy<u64> = Lazy::new(|| {
+    std::env::var("SCHEDULER_LEADER_STALE_SECONDS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|seconds| *seconds >= 2 * HEARTBEAT_INTERVAL.as_secs())
+        .unwrap_or(DEFAULT_STALE_SECONDS)
+});
+
+/// The connection holding this instance's scheduler locks, and the schedulers it leads
+#[derive(Default)]
+struct LeaderSession {
+    conn: Option<PgConnection>,
+    backend_pid: i32,
+    held: BTreeSet<String>,
+}
+
+impl LeaderSession {
+    /// The lock session and its backend pid, connecting first if needed
+    async fn connection(&mut self, pool: &PgPool) -> Result<(&mut PgConnection, i32)> {
+        let conn = match self.conn.take() {
+            Some(conn) => conn,
+            None => {
+                // Detached: the locks live as long as this session, not a pool checkout
+                let mut conn = pool.acquire().await?.detach();
+                self.backend_pid = sqlx::query_scalar("SELECT pg_backend_pid()").fetch_one(&mut conn).await?;
+                conn
+            }
+        };
+        let backend_pid = self.backend_pid;
+        Ok((self.conn.insert(conn), backend_pid))
+    }
+
+    async fn try_acquire(&mut self, pool: &PgPool, scheduler: &str) -> Result<bool> {
+        let (conn, backend_pid) = self.connection(pool).await?;
+
+        let acquired: bool = sqlx::query_scalar("SELECT pg_try_advisory_lock(hashtext('scheduler:' || $1))")
+            .bind(scheduler)
+            .fetch_one(&mut *conn)
+            .await?;
+        if !acquired {
+            return Ok(false);
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO scheduler_leaders (scheduler, instance_id, backend_pid, acquired_at, heartbeat_at)
+            VALUES ($1, $2, $3, NOW(), NOW())
+            ON CONFLICT (scheduler) DO UPDATE
+            SET instance_id = EXCLUDED.instance_id,
+                backend_pid = EXCLUDED.backend_pid,
+                acquired_at = NOW(),
+                heartbeat_at = NOW()
+            "#,
+        )
+        .bind(scheduler)
+        .bind(INSTANCE_ID.as_str())
+        .bind(backend_pid)
+        .execute(&mut *conn)
+        .await?;
+
+        self.held.insert(scheduler.to_string());
+        Ok(true)
+    }
+
+    /// Terminate the session of a leader that stopped heartbeating; the scheduler is
+    /// then free for the next tick. Returns the instance that was taken over from.
+    async fn take_over_stale(pool: &PgPool, scheduler: &str) -> Result<Option<String>> {
+        let stale: Option<(String, i32)> = sqlx::query_as(
+            r#"
+            SELECT instance_id, backend_pid FROM scheduler_leaders
+            WHERE scheduler = $1 AND heartbeat_at < NOW() - make_interval(secs => $2)
+            "#,
+        )
+        .bind(scheduler)
+        .bind(*STALE_SECONDS as f64)
+        .fetch_optional(pool)
+        .await?;
+
+        let Some((instance_id, backend_pid)) = stale else {
+            return Ok(None);
+        };
+
+        // Only while that backend still holds advisory locks: the pid may have been reused
+        let terminated: Option<bool> = sqlx::query_scalar(
+            r#"
+            SELECT pg_terminate_backend(pid) FROM pg_locks
+            WHERE locktype = 'advisory' AND granted AND pid = $1
+            LIMIT 1
+            "#,
+        )
+        .bind(backend_pid)
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(terminated.unwrap_or(false).then_some(instance_id))
+    }
+
+    /// Record the heartbeat of every held lock; losing the connection loses them all
+    async fn heartbeat(&mut self) {
+        if self.held.is_empty() {
+            return;
+        }
+        let Some(conn) = self.conn.as_mut() else {
+            return;
+        };
+
+        let held: Vec<String> = self.held.iter().cloned().collect();
+        let result = sqlx::query(
+            "UPDATE scheduler_leaders SET heartbeat_at = NOW() WHERE scheduler = ANY($1) AND backend_pid = $2",
+        )
+        .bind(&held)
+        .bind(self.backend_pid)
+        .execute(&mut *conn)
+        .await;
+
+        if let Err(e) = result {
+            tracing::warn!("Scheduler leader connection lost, no longer leading {:?}: {:?}", held, e);
+            self.step_down();
+        }
+    }
+
+    fn step_down(&mut self) {
+        for scheduler in std::mem::take(&mut self.held) {
+            metrics::record_scheduler_leader(&scheduler, false);
+        }
+        // Dropping the session releases whatever locks Postgres still has for it
+        self.conn = None;
+    }
+}
+
+pub struct SchedulerLeader;
+
+impl SchedulerLeader {
+    /// Enable leader election and start the heartbeat. Without it every instance leads
+    /// every scheduler (single-instance tools and tests).
+    pub fn init(pool: PgPool) {
+        if POOL.set(pool).is_ok() {
+            tokio::spawn(Self::run_heartbeat());
+        }
+    }
+
+    /// Identifies this instance in `scheduler_leaders`
+    pub fn instance_id() -> &'static str {
+        INSTANCE_ID.as_str()
+    }
+
+    /// Heartbeat age after which a leader counts as gone
+    pub fn stale_seconds() -> u64 {
+        *STALE_SECONDS
+    }
+
+    /// Whether this instance should run `scheduler` now: it leads it already, or just
+    /// took leadership because no other instance held it
+    pub async fn leads(scheduler: &str) -> bool {
+        let Some(pool) = POOL.get() else {
+            return true;
+        };
+
+        let mut session = SESSION.lock().await;
+        if session.held.contains(scheduler) {
+            return true;
+        }
+
+        match session.try_acquire(pool, scheduler).await {
+            Ok(true) => {
+                tracing::info!("Instance {} now leads the {} scheduler", Self::instance_id(), scheduler);
+                metrics::record_scheduler_leader(scheduler, true);
+                true
+            }
+            Ok(false) => {
+                metrics::record_scheduler_leader(scheduler, false);
+                match LeaderSession::take_over_stale(pool, scheduler).await {
+                    Ok(Some(instance_id)) => tracing::warn!(
+                        "The {} scheduler leader {} stopped heartbeating; terminated its session",
+                        scheduler,
+                        instance_id
+                    ),
+                    Ok(None) => {}
+                    Err(e) => tracing::error!("Failed to take over the {} scheduler: {:?}", scheduler, e),
+                }
+                tracing::debug!("Another instance leads the {} scheduler, skipping run", scheduler);
+                false
+            }
+            Err(e) => {
+                tracing::error!("Leader election for the {} scheduler failed: {:?}", scheduler, e);
+                session.step_down();
+                false
+            }
+        }
+    }
+
+    /// Give up leadership of every scheduler, so other instances take over without
+    /// waiting for this connection to close
+    pub async fn release_all() {
+        let mut session = SESSION.lock().await;
+        if session.held.is_empty() {
+            return;
+        }
+
+        let held: Vec<String> = session.held.iter().cloned().collect();
+        let backend_pid = session.backend_pid;
+        if let Some(mut conn) = session.conn.take() {
+            let result = sqlx::query("DELETE FROM scheduler_leaders WHERE scheduler = ANY($1) AND backend_pid = $2")
+                .bind(&held)
+                .bind(backend_pid)
+                .execute(&mut conn)
+                .await;
+            if let Err(e) = result {
+                tracing::warn!("Failed to clear scheduler leadership records: {:?}", e);
+            }
+            // Closing the session releases its advisory locks
+            let _ = conn.close().await;
+        }
+
+        tracing::info!("Released leadership of {:?}", held);
+        session.step_down();
+    }
+
+    async fn run_heartbeat() {
+        let mut ticker = tokio::time::interval(HEARTBEAT_INTERVAL);
+        loop {
+            ticker.tick().await;
+            SESSION.lock().await.heartbeat().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_every_instance_leads_without_election() {
+        assert!(SchedulerLeader::leads("openfda").await);
+        assert!(SchedulerLeader::stale_seconds() >= 2 * HEARTBEAT_INTERVAL.as_secs());
+    }
+}
@@ -0,0 +1,434 @@
+/// Search index pipeline
+///
+/// Triggers (migration 088) put the key of every changed listing, OpenFDA entry and EMA
+/// entry into `search_index_queue`. The worker drains it in batches: it loads the
+/// current rows, upserts their documents and deletes the documents of keys that no
+/// longer exist or are no longer listed. A batch is removed from the queue only when
+/// the engine accepted it, so nothing is lost while the engine is down; instances share
+/// the queue through `FOR UPDATE SKIP LOCKED`.
+///
+/// On startup the worker enables the indexes (queueing a full reindex of any that were
+/// disabled). An instance without a search engine disables them and clears the queue,
+/// so every instance must use the same `SEARCH_ENGINE` configuration.
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use dashmap::DashSet;
+use once_cell::sync::Lazy;
+use serde_json::Value;
+use sqlx::PgPool;
+use uuid::Uuid;
+use super::SearchEngine;
+use crate::middleware::error_handling::{AppError, Result};
+use crate::middleware::metrics;
+use crate::models::search_engine::{ReindexResponse, SearchEngineStatus, SearchIndex, SearchIndexStatus};
+
+/// Queue entries per batch
+const BATCH_SIZE: i64 = 500;
+
+/// Pause between polls of an empty queue, and after a failed batch
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+const RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Indexes fully built in the engine; searches on the others use Postgres
+static READY_INDEXES: Lazy<DashSet<SearchIndex>> = Lazy::new(DashSet::new);
+
+/// Whether searches on this index may use the engine
+pub fn is_ready(index: SearchIndex) -> bool {
+    READY_INDEXES.contains(&index)
+}
+
+pub struct SearchIndexWorker {
+    pool: PgPool,
+    engine: Option<Arc<dyn SearchEngine>>,
+}
+
+impl SearchIndexWorker {
+    pub fn new(pool: PgPool, engine: Option<Arc<dyn SearchEngine>>) -> Self {
+        Self { pool, engine }
+    }
+
+    pub async fn run(&self) {
+        let Some(engine) = self.engine.clone() else {
+            match disable_indexing(&self.pool).await {
+                Ok(true) => tracing::warn!("No search engine configured: search indexing disabled, searches use Postgres"),
+                Ok(false) => {}
+                Err(e) => tracing::error!("Failed to disable search indexing: {:?}", e),
+            }
+            return;
+        };
+
+        // The engine may start after the API; keep trying until the indexes exist
+        for index in SearchIndex::ALL {
+            while let Err(e) = self.enable(engine.as_ref(), index).await {
+                tracing::error!("Search index {} setup failed, retrying: {:?}", index.as_str(), e);
+                metrics::record_search_engine_failure(index.as_str(), "index");
+                tokio::time::sleep(RETRY_INTERVAL).await;
+            }
+        }
+
+        loop {
+            match self.index_batch(engine.as_ref()).await {
+                // A full batch: more are likely waiting
+                Ok(processed) if processed == BATCH_SIZE as usize => continue,
+                Ok(_) => {
+                    if let Err(e) = self.refresh_ready().await {
+                        tracing::error!("Failed to refresh search index state: {:?}", e);
+                    }
+                    tokio::time::sleep(POLL_INTERVAL).await;
+                }
+                Err(e) => {
+                    tracing::error!("Search index batch failed, retrying in {}s: {:?}", RETRY_INTERVAL.as_secs(), e);
+                    tokio::time::sleep(RETRY_INTERVAL).await;
+                }
+            }
+        }
+    }
+
+    /// Apply the index settings and start queueing changes; an index that wasn't
+    /// enabled gets a full reindex, queued in the same transaction so no change is missed
+    async fn enable(&self, engine: &dyn SearchEngine, index: SearchIndex) -> Result<()> {
+        engine.configure_index(index).await?;
+
+        let mut tx = self.pool.begin().await?;
+        let newly_enabled: Option<String> = sqlx::query_scalar(
+            r#"
+            UPDATE search_indexes
+            SET enabled = true, ready_at = NULL, reindex_requested_at = NOW(), updated_at = NOW()
+            WHERE name = $1 AND NOT enabled
+            RETURNING name
+            "#
+        )
+        .bind(index.as_str())
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if newly_enabled.is_some() {
+            let queued = queue_all(&mut tx, index).await?;
+            tracing::info!("Search index {} enabled; queued {} records for the initial build", index.as_str(), queued);
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Index one batch from the queue; returns the number of queue entries processed
+    async fn index_batch(&self, engine: &dyn SearchEngine) -> Result<usize> {
+        let mut tx = self.pool.begin().await?;
+
+        let entries: Vec<(String, String)> = sqlx::query_as(
+            r#"
+            DELETE FROM search_index_queue
+            WHERE id IN (
+                SELECT id FROM search_index_queue
+                ORDER BY id
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING index_name, record_key
+            "#
+        )
+        .bind(BATCH_SIZE)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        if entries.is_empty() {
+            return Ok(0);
+        }
+
+        let mut keys_by_index: HashMap<String, BTreeSet<String>> = HashMap::new();
+        for (index_name, key) in &entries {
+            keys_by_index.entry(index_name.clone()).or_default().insert(key.clone());
+        }
+
+        for (index_name, keys) in keys_by_index {
+            let index = SearchIndex::parse(&index_name)?;
+            let keys: Vec<String> = keys.into_iter().collect();
+            let documents = load_documents(&self.pool, index, &keys).await?;
+
+            let found: BTreeSet<&str> = documents.iter().map(|(key, _)| key.as_str()).collect();
+            let removed: Vec<String> = keys.iter().filter(|k| !found.contains(k.as_str())).cloned().collect();
+            let documents: Vec<Value> = documents.into_iter().map(|(_, document)| document).collect();
+
+            let upserted = documents.len();
+            let deleted = removed.len();
+            let result = async {
+                if !documents.is_empty() {
+                    engine.upsert_documents(index, documents).await?;
+                }
+                if !removed.is_empty() {
+                    engine.delete_documents(index, removed).await?;
+                }
+                Ok::<_, AppError>(())
+            }
+            .await;
+
+            // Dropping the transaction puts the whole batch back in the queue
+            if let Err(e) = result {
+                metrics::record_search_engine_failure(index.as_str(), "index");
+                return Err(e);
+            }
+            metrics::record_search_indexed(index.as_str(), "upsert", upserted);
+            metrics::record_search_indexed(index.as_str(), "delete", deleted);
+        }
+
+        tx.commit().await?;
+        Ok(entries.len())
+    }
+
+    /// Mark indexes whose build has drained as ready, and load the ready set. Reads the
+    /// table, so instances agree on readiness whichever of them did the indexing.
+    async fn refresh_ready(&self) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE search_indexes s
+            SET ready_at = NOW(), updated_at = NOW()
+            WHERE enabled AND ready_at IS NULL
+              AND NOT EXISTS (SELECT 1 FROM search_index_queue q WHERE q.index_name = s.name)
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+
+        let ready: Vec<String> = sqlx::query_scalar(
+            "SELECT name FROM search_indexes WHERE enabled AND ready_at IS NOT NULL"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        for index in SearchIndex::ALL {
+            if ready.iter().any(|name| name == index.as_str()) {
+                if READY_INDEXES.insert(index) {
+                    tracing::info!("Search index {} is built; searches use the search engine", index.as_str());
+                }
+            } else {
+                READY_INDEXES.remove(&index);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Stop queueing changes; true when indexing was enabled
+async fn disable_indexing(pool: &PgPool) -> Result<bool> {
+    let mut tx = pool.begin().await?;
+    let disabled = sqlx::query(
+        "UPDATE search_indexes SET enabled = false, ready_at = NULL, updated_at = NOW() WHERE enabled"
+    )
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+    sqlx::query("DELETE FROM search_index_queue").execute(&mut *tx).await?;
+    tx.commit().await?;
+
+    READY_INDEXES.clear();
+    Ok(disabled > 0)
+}
+
+/// Queue every record of an index. Marketplace queues all listings; hidden ones are
+/// removed from the engine when indexed.
+async fn queue_all(tx: &mut sqlx::Transaction<'_, sqlx::Postgres>, index: SearchIndex) -> Result<u64> {
+    let sql = match index {
+        SearchIndex::Marketplace => "INSERT INTO search_index_queue (index_name, record_key) SELECT 'marketplace', id::text FROM inventory",
+        SearchIndex::OpenFda => "INSERT INTO search_index_queue (index_name, record_key) SELECT 'openfda', product_ndc FROM openfda_catalog",
+        SearchIndex::Ema => "INSERT INTO search_index_queue (index_name, record_key) SELECT 'ema', eu_number FROM ema_catalog",
+    };
+
+    Ok(sqlx::query(sql).execute(&mut **tx).await?.rows_affected())
+}
+
+/// Current documents of the given keys, with their keys; keys of missing records and
+/// listings not on the marketplace are left out
+async fn load_documents(pool: &PgPool, index: SearchIndex, keys: &[String]) -> Result<Vec<(String, Value)>> {
+    let documents = match index {
+        SearchIndex::Marketplace => {
+            let ids: Vec<Uuid> = keys.iter().filter_map(|key| key.parse().ok()).collect();
+            sqlx::query_as(
+                r#"
+                SELECT i.id::text, jsonb_build_object(
+                    'key', i.id::text,
+                    'pharmaceutical_id', p.id::text,
+                    'brand_name', p.brand_name,
+                    'generic_name', p.generic_name,
+                    'manufacturer', p.manufacturer,
+                    'ndc_code', p.ndc_code,
+                    'category', p.category,
+                    'description', p.description,
+                    'strength', p.strength,
+                    'dosage_form', p.dosage_form,
+                    'product_domain', p.product_domain,
+                    'seller_name', u.company_name,
+                    'seller_verified', u.is_verified,
+                    'seller_country', u.country_code,
+                    'quantity', i.quantity,
+                    'unit_price', i.unit_price::float8,
+                    'expiry_date_ts', EXTRACT(EPOCH FROM i.expiry_date::timestamp)::bigint,
+                    'created_at_ts', EXTRACT(EPOCH FROM i.created_at)::bigint,
+                    'updated_at_ts', EXTRACT(EPOCH FROM i.updated_at)::bigint
+                )
+                FROM inventory i
+                JOIN pharmaceuticals p ON i.pharmaceutical_id = p.id
+                JOIN users u ON i.user_id = u.id
+                WHERE i.id = ANY($1)
+                  AND i.status = 'available' AND i.taken_down_at IS NULL
+                  AND u.listing_suspended_at IS NULL AND u.suspended_at IS NULL
+                "#
+            )
+            .bind(ids)
+            .fetch_all(pool)
+            .await?
+        }
+        SearchIndex::OpenFda => {
+            sqlx::query_as(
+                r#"
+                SELECT product_ndc, jsonb_build_object(
+                    'key', product_ndc,
+                    'brand_name', brand_name,
+                    'generic_name', generic_name,
+                    'product_ndc', product_ndc,
+                    'labeler_name', labeler_name,
+                    'substances', CASE WHEN jsonb_typeof(active_ingredients) = 'array'
+                        THEN ARRAY(SELECT a->>'name' FROM jsonb_array_elements(active_ingredients) a)
+                    END,
+                    'pharm_class', pharm_class,
+                    'dosage_form', dosage_form,
+                    'route', route,
+                    'product_type', product_type,
+                    'marketing_category', marketing_category,
+                    'dea_schedule', dea_schedule
+                )
+                FROM openfda_catalog
+                WHERE product_ndc = ANY($1)
+                "#
+            )
+            .bind(keys)
+            .fetch_all(pool)
+            .await?
+        }
+        SearchIndex::Ema => {
+            sqlx::query_as(
+                r#"
+                SELECT eu_number, jsonb_build_object(
+                    'key', eu_number,
+                    'product_name', product_name,
+                    'inn_name', inn_name,
+                    'eu_number', eu_number,
+                    'mah_name', mah_name,
+                    'active_substances', CASE WHEN jsonb_typeof(active_substances) = 'array'
+                        THEN ARRAY(SELECT COALESCE(s->>'name', s#>>'{}') FROM jsonb_array_elements(active_substances) s)
+                    END,
+                    'atc_code', atc_code,
+                    'therapeutic_indication', therapeutic_indication,
+                    'therapeutic_area', therapeutic_area,
+                    'pharmaceutical_form', pharmaceutical_form,
+                    'authorization_status', authorization_status,
+                    'language_code', language_code,
+                    'orphan_designation', orphan_designation
+                )
+                FROM ema_catalog
+                WHERE eu_number = ANY($1)
+                "#
+            )
+            .bind(keys)
+            .fetch_all(pool)
+            .await?
+        }
+    };
+
+    Ok(documents)
+}
+
+/// Index status and reindexing for the admin API
+pub struct SearchIndexService {
+    pool: PgPool,
+    engine: Option<Arc<dyn SearchEngine>>,
+}
+
+impl SearchIndexService {
+    pub fn new(pool: PgPool, engine: Option<Arc<dyn SearchEngine>>) -> Self {
+        Self { pool, engine }
+    }
+
+    pub async fn status(&self) -> Result<SearchEngineStatus> {
+        #[allow(clippy::type_complexity)]
+        let rows: Vec<(String, bool, Option<DateTime<Utc>>, Option<DateTime<Utc>>, i64, Option<DateTime<Utc>>)> =
+            sqlx::query_as(
+                r#"
+                SELECT s.name, s.enabled, s.ready_at, s.reindex_requested_at,
+                       COUNT(q.id), MIN(q.queued_at)
+                FROM search_indexes s
+                LEFT JOIN search_index_queue q ON q.index_name = s.name
+                GROUP BY s.name, s.enabled, s.ready_at, s.reindex_requested_at
+                ORDER BY s.name
+                "#
+            )
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut indexes = Vec::with_capacity(rows.len());
+        for (name, enabled, ready_at, reindex_requested_at, queued, oldest_queued_at) in rows {
+            let index = SearchIndex::parse(&name)?;
+            let documents = match &self.engine {
+                Some(engine) => engine.document_count(index).await.ok(),
+                None => None,
+            };
+            indexes.push(SearchIndexStatus {
+                index,
+                enabled,
+                ready_at,
+                reindex_requested_at,
+                queued,
+                oldest_queued_at,
+                documents,
+            });
+        }
+
+        Ok(SearchEngineStatus {
+            engine: self.engine.as_ref().map(|engine| engine.name().to_string()),
+            indexes,
+        })
+    }
+
+    /// Queue every record of the given indexes (all when None). The indexes stay in
+    /// use while the worker re-sends their documents.
+    pub async fn reindex(&self, index: Option<SearchIndex>) -> Result<ReindexResponse> {
+        if self.engine.is_none() {
+            return Err(AppError::BadRequest("No search engine is configured (SEARCH_ENGINE)".to_string()));
+        }
+
+        let indexes: Vec<SearchIndex> = match index {
+            Some(index) => vec![index],
+            None => SearchIndex::ALL.to_vec(),
+        };
+
+        let mut tx = self.pool.begin().await?;
+        let mut queued = 0;
+        for index in &indexes {
+            let enabled: bool = sqlx::query_scalar(
+                r#"
+                UPDATE search_indexes SET reindex_requested_at = NOW(), updated_at = NOW()
+                WHERE name = $1
+                RETURNING enabled
+                "#
+            )
+            .bind(index.as_str())
+            .fetch_one(&mut *tx)
+            .await?;
+
+            if !enabled {
+                return Err(AppError::BadRequest(format!(
+                    "Search index {} is not enabled yet; the search index worker enables it on startup",
+                    index.as_str()
+                )));
+            }
+            queued += queue_all(&mut tx, *index).await? as i64;
+        }
+        tx.commit().await?;
+
+        tracing::info!("Search reindex queued {} records for {:?}", queued, indexes);
+        Ok(ReindexResponse { indexes, queued })
+    }
+}
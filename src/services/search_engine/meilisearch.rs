@@ -0,0 +1,271 @@
+/// Meilisearch backend (https://www.meilisearch.com/docs/reference/api/overview)
+///
+/// Index uids are `SEARCH_ENGINE_INDEX_PREFIX` (default `atlas_`) plus the index name.
+/// Meilisearch document ids only allow letters, digits, `-` and `_`, so each document's
+/// `id` is its key with every other character (and `_`) written as `_` plus two hex
+/// digits; EU numbers such as `EU/1/08/470/001` contain slashes. Searches only retrieve
+/// the `key` field. Writes are asynchronous tasks in Meilisearch: they are accepted here
+/// and applied by the engine within moments.
+
+use std::time::Duration;
+use futures::future::BoxFuture;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use super::SearchEngine;
+use crate::middleware::error_handling::{AppError, Result};
+use crate::models::search_engine::{SearchFilter, SearchHits, SearchIndex, SearchQuery, SortOrder};
+
+#[derive(Debug, Clone)]
+pub struct MeilisearchEngine {
+    http_client: reqwest::Client,
+    base_url: String,
+    api_key: Option<String>,
+    index_prefix: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchResponse {
+    hits: Vec<Hit>,
+    #[serde(default)]
+    estimated_total_hits: Option<u64>,
+    #[serde(default)]
+    facet_distribution: Option<std::collections::BTreeMap<String, std::collections::BTreeMap<String, u64>>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Hit {
+    key: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IndexStats {
+    number_of_documents: u64,
+}
+
+impl MeilisearchEngine {
+    /// From `SEARCH_ENGINE_URL`, `SEARCH_ENGINE_API_KEY` and `SEARCH_ENGINE_INDEX_PREFIX`
+    pub fn from_env() -> anyhow::Result<Self> {
+        let base_url = std::env::var("SEARCH_ENGINE_URL")
+            .map_err(|_| anyhow::anyhow!("SEARCH_ENGINE_URL is required with SEARCH_ENGINE=meilisearch"))?;
+        let timeout_ms = std::env::var("SEARCH_ENGINE_TIMEOUT_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(2_000);
+
+        let http_client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(timeout_ms))
+            .user_agent("Atlas-Pharma-Search/1.0")
+            .build()?;
+
+        tracing::info!("✅ Search engine configured: Meilisearch at {}", base_url);
+
+        Ok(Self {
+            http_client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key: std::env::var("SEARCH_ENGINE_API_KEY").ok().filter(|k| !k.is_empty()),
+            index_prefix: std::env::var("SEARCH_ENGINE_INDEX_PREFIX").unwrap_or_else(|_| "atlas_".to_string()),
+        })
+    }
+
+    fn uid(&self, index: SearchIndex) -> String {
+        format!("{}{}", self.index_prefix, index.as_str())
+    }
+
+    async fn request(&self, method: reqwest::Method, path: &str, body: Option<Value>) -> Result<Value> {
+        let mut request = self.http_client.request(method, format!("{}{}", self.base_url, path));
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| AppError::Internal(anyhow::anyhow!("Meilisearch request to {} failed: {}", path, e)))?;
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+
+        if !status.is_success() {
+            return Err(AppError::Internal(anyhow::anyhow!(
+                "Meilisearch {} returned {}: {}",
+                path,
+                status,
+                body.chars().take(500).collect::<String>()
+            )));
+        }
+
+        Ok(serde_json::from_str(&body).unwrap_or(Value::Null))
+    }
+}
+
+/// Meilisearch-safe document id for a key; distinct keys get distinct ids
+pub(crate) fn document_id(key: &str) -> String {
+    let mut id = String::with_capacity(key.len());
+    for byte in key.bytes() {
+        if byte.is_ascii_alphanumeric() || byte == b'-' {
+            id.push(byte as char);
+        } else {
+            id.push_str(&format!("_{:02X}", byte));
+        }
+    }
+    id
+}
+
+fn filter_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\"")),
+        other => other.to_string(),
+    }
+}
+
+/// Filter expressions, all of which must match
+pub(crate) fn filter_expressions(filters: &[SearchFilter]) -> Vec<String> {
+    filters
+        .iter()
+        .map(|filter| match filter {
+            SearchFilter::Equals(field, value) => format!("{} = {}", field, filter_value(value)),
+            SearchFilter::AtLeast(field, value) => format!("{} >= {}", field, filter_value(value)),
+            SearchFilter::AtMost(field, value) => format!("{} <= {}", field, filter_value(value)),
+        })
+        .collect()
+}
+
+impl SearchEngine for MeilisearchEngine {
+    fn name(&self) -> &'static str {
+        "meilisearch"
+    }
+
+    fn configure_index<'a>(&'a self, index: SearchIndex) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let uid = self.uid(index);
+            // Fails as a task (not here) when the index already exists
+            self.request(reqwest::Method::POST, "/indexes", Some(json!({ "uid": uid, "primaryKey": "id" })))
+                .await?;
+
+            let settings = index.settings();
+            let filterable: Vec<&str> = settings.filterable.iter().chain(settings.facets).copied().collect();
+            self.request(
+                reqwest::Method::PATCH,
+                &format!("/indexes/{}/settings", uid),
+                Some(json!({
+                    "searchableAttributes": settings.searchable,
+                    "filterableAttributes": filterable,
+                    "sortableAttributes": settings.sortable,
+                    "typoTolerance": { "enabled": true },
+                })),
+            )
+            .await?;
+
+            Ok(())
+        })
+    }
+
+    fn upsert_documents<'a>(&'a self, index: SearchIndex, documents: Vec<Value>) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let documents: Vec<Value> = documents
+                .into_iter()
+                .map(|mut document| {
+                    let id = document.get("key").and_then(Value::as_str).map(document_id);
+                    if let (Some(id), Some(fields)) = (id, document.as_object_mut()) {
+                        fields.insert("id".to_string(), Value::String(id));
+                    }
+                    document
+                })
+                .collect();
+
+            self.request(
+                reqwest::Method::POST,
+                &format!("/indexes/{}/documents", self.uid(index)),
+                Some(Value::Array(documents)),
+            )
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn delete_documents<'a>(&'a self, index: SearchIndex, keys: Vec<String>) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let ids: Vec<String> = keys.iter().map(|key| document_id(key)).collect();
+            self.request(
+                reqwest::Method::POST,
+                &format!("/indexes/{}/documents/delete-batch", self.uid(index)),
+                Some(json!(ids)),
+            )
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn search<'a>(&'a self, index: SearchIndex, query: &'a SearchQuery) -> BoxFuture<'a, Result<SearchHits>> {
+        Box::pin(async move {
+            let mut body = json!({
+                "q": query.text,
+                "limit": query.limit.max(0),
+                "offset": query.offset.max(0),
+                "filter": filter_expressions(&query.filters),
+                "facets": query.facets,
+                "attributesToRetrieve": ["key"],
+            });
+            if !query.fields.is_empty() {
+                body["attributesToSearchOn"] = json!(query.fields);
+            }
+            if let Some((field, order)) = query.sort {
+                let order = match order {
+                    SortOrder::Asc => "asc",
+                    SortOrder::Desc => "desc",
+                };
+                body["sort"] = json!([format!("{}:{}", field, order)]);
+            }
+
+            let response = self
+                .request(reqwest::Method::POST, &format!("/indexes/{}/search", self.uid(index)), Some(body))
+                .await?;
+            let response: SearchResponse = serde_json::from_value(response)
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Unexpected Meilisearch search response: {}", e)))?;
+
+            Ok(SearchHits {
+                total: response.estimated_total_hits.unwrap_or(response.hits.len() as u64),
+                keys: response.hits.into_iter().map(|hit| hit.key).collect(),
+                facets: response.facet_distribution.unwrap_or_default(),
+            })
+        })
+    }
+
+    fn document_count<'a>(&'a self, index: SearchIndex) -> BoxFuture<'a, Result<u64>> {
+        Box::pin(async move {
+            let stats = self
+                .request(reqwest::Method::GET, &format!("/indexes/{}/stats", self.uid(index)), None)
+                .await?;
+            let stats: IndexStats = serde_json::from_value(stats)
+                .map_err(|e| AppError::Internal(anyhow::anyhow!("Unexpected Meilisearch stats response: {}", e)))?;
+            Ok(stats.number_of_documents)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_document_ids_and_filters() {
+        assert_eq!(document_id("0002-1433"), "0002-1433");
+        assert_eq!(document_id("EU/1/08/470/001"), "EU_2F1_2F08_2F470_2F001");
+        assert_ne!(document_id("EU_2F1"), document_id("EU/1"));
+
+        let filters = filter_expressions(&[
+            SearchFilter::Equals("manufacturer", json!("Smith \"&\" Co")),
+            SearchFilter::AtLeast("quantity", json!(5)),
+            SearchFilter::AtMost("unit_price", json!(12.5)),
+        ]);
+        assert_eq!(filters, vec![
+            r#"manufacturer = "Smith \"&\" Co""#.to_string(),
+            "quantity >= 5".to_string(),
+            "unit_price <= 12.5".to_string(),
+        ]);
+    }
+}
@@ -0,0 +1,96 @@
+// Search Engine Module
+// Optional external search engine for marketplace and catalog search: typo-tolerant
+// matching and facet counts. Postgres stays the source of truth; the index worker
+// mirrors changed rows into the engine, and every search falls back to the Postgres
+// queries when no engine is configured, the index is still being built, or the engine
+// fails.
+
+pub mod meilisearch;
+pub mod index_worker;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use futures::future::BoxFuture;
+use serde_json::Value;
+use crate::middleware::error_handling::Result;
+use crate::middleware::metrics;
+use crate::models::search_engine::{SearchHits, SearchIndex, SearchQuery};
+
+pub use meilisearch::MeilisearchEngine;
+pub use index_worker::{SearchIndexService, SearchIndexWorker};
+
+/// A search backend. Documents are JSON objects with a `key` field holding the
+/// record's natural key (listing id, NDC, EU number); searches return keys, which
+/// callers load from Postgres.
+pub trait SearchEngine: Send + Sync + std::fmt::Debug {
+    /// Reported in search responses and the admin status
+    fn name(&self) -> &'static str;
+
+    /// Create the index when missing and apply its `IndexSettings`
+    fn configure_index<'a>(&'a self, index: SearchIndex) -> BoxFuture<'a, Result<()>>;
+
+    /// Add or replace documents
+    fn upsert_documents<'a>(&'a self, index: SearchIndex, documents: Vec<Value>) -> BoxFuture<'a, Result<()>>;
+
+    fn delete_documents<'a>(&'a self, index: SearchIndex, keys: Vec<String>) -> BoxFuture<'a, Result<()>>;
+
+    fn search<'a>(&'a self, index: SearchIndex, query: &'a SearchQuery) -> BoxFuture<'a, Result<SearchHits>>;
+
+    fn document_count<'a>(&'a self, index: SearchIndex) -> BoxFuture<'a, Result<u64>>;
+}
+
+/// Engine from `SEARCH_ENGINE`; None when unset or `postgres`. Only Meilisearch is
+/// implemented; other engines plug in through the `SearchEngine` trait.
+pub fn from_env() -> anyhow::Result<Option<Arc<dyn SearchEngine>>> {
+    let engine = std::env::var("SEARCH_ENGINE").unwrap_or_default().trim().to_lowercase();
+    match engine.as_str() {
+        "" | "postgres" => Ok(None),
+        "meilisearch" => Ok(Some(Arc::new(MeilisearchEngine::from_env()?))),
+        other => anyhow::bail!("Unsupported SEARCH_ENGINE '{}' (supported: meilisearch, postgres)", other),
+    }
+}
+
+/// Engine hits for `query`, or None when the caller has to search Postgres: no engine
+/// is configured, the index isn't fully built yet, or the engine failed. Records which
+/// backend answered.
+pub async fn search_with_fallback(
+    engine: Option<&Arc<dyn SearchEngine>>,
+    index: SearchIndex,
+    query: Option<&SearchQuery>,
+) -> Option<SearchHits> {
+    let hits = match (engine, query) {
+        (Some(engine), Some(query)) if index_worker::is_ready(index) => match engine.search(index, query).await {
+            Ok(hits) => Some(hits),
+            Err(e) => {
+                tracing::warn!("{} search on {} failed, falling back to Postgres: {}", engine.name(), index.as_str(), e);
+                metrics::record_search_engine_failure(index.as_str(), "search");
+                None
+            }
+        },
+        _ => None,
+    };
+
+    metrics::record_search_request(index.as_str(), if hits.is_some() { "engine" } else { "postgres" });
+    hits
+}
+
+/// Records loaded for engine hits, in hit order. Keys without a record (deleted or
+/// hidden since they were indexed) are skipped.
+pub fn in_hit_order<T>(records: Vec<T>, keys: &[String], key: impl Fn(&T) -> String) -> Vec<T> {
+    let mut by_key: HashMap<String, T> = records.into_iter().map(|record| (key(&record), record)).collect();
+    keys.iter().filter_map(|k| by_key.remove(k)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_hit_order() {
+        let keys = vec!["c".to_string(), "a".to_string(), "gone".to_string(), "b".to_string()];
+        let records = vec![("a", 1), ("b", 2), ("c", 3)];
+
+        let ordered = in_hit_order(records, &keys, |r| r.0.to_string());
+        assert_eq!(ordered, vec![("c", 3), ("a", 1), ("b", 2)]);
+    }
+}
@@ -0,0 +1,257 @@
+/// Semantic Search Service
+///
+/// Searches pharmaceuticals, marketplace inventory and the OpenFDA / EMA catalogs by
+/// meaning as well as by words: vector similarity over `search_embeddings` is blended
+/// with the full-text `ts_rank` of each source. Backfill jobs keep the embeddings in step
+/// with their records, and the IVFFlat index is rebuilt as the table grows.
+
+use std::collections::HashMap;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::{
+    middleware::error_handling::{AppError, Result},
+    models::semantic_search::*,
+    repositories::SemanticSearchRepository,
+    services::ClaudeEmbeddingService,
+};
+
+const DEFAULT_LIMIT: i64 = 20;
+const MAX_LIMIT: i64 = 50;
+
+/// Candidates taken from each ranking per requested result
+const CANDIDATE_FACTOR: i64 = 3;
+
+/// Records read, hashed and embedded per backfill step
+const BACKFILL_BATCH_SIZE: i64 = 500;
+
+pub struct SemanticSearchService {
+    repo: SemanticSearchRepository,
+    embeddings: ClaudeEmbeddingService,
+}
+
+impl SemanticSearchService {
+    pub fn new(db_pool: PgPool) -> Result<Self> {
+        // Record embeddings are computed locally; the key is only used for quota tracking
+        let api_key = std::env::var("ANTHROPIC_API_KEY").unwrap_or_default();
+        let embeddings = ClaudeEmbeddingService::new(db_pool.clone(), api_key, Uuid::nil())?;
+
+        Ok(Self {
+            repo: SemanticSearchRepository::new(db_pool),
+            embeddings,
+        })
+    }
+
+    pub async fn search(&self, request: &SemanticSearchRequest) -> Result<SemanticSearchResponse> {
+        let query_text = request.query_text()
+            .ok_or_else(|| AppError::BadRequest("Query parameter 'q' is required".to_string()))?;
+        let types = request.type_list()?;
+        let weight = request.weight();
+        let limit = request.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+        let candidates = limit * CANDIDATE_FACTOR;
+
+        let vector = if weight > 0.0 {
+            let embedding = self.embeddings.generate_embedding(query_text).await?;
+            self.repo.vector_candidates(&embedding, &types, candidates).await?
+        } else {
+            Vec::new()
+        };
+        let text = if weight < 1.0 {
+            self.repo.text_candidates(query_text, &types, candidates).await?
+        } else {
+            Vec::new()
+        };
+
+        Ok(SemanticSearchResponse {
+            query: query_text.to_string(),
+            results: blend_candidates(vector, text, weight, limit as usize),
+            types,
+            semantic_weight: weight,
+        })
+    }
+
+    // ========================================================================
+    // Backfill
+    // ========================================================================
+
+    /// Queue a backfill job and run it in the background
+    pub async fn start_backfill(
+        db_pool: PgPool,
+        request: &StartEmbeddingBackfillRequest,
+        created_by: Option<Uuid>,
+    ) -> Result<EmbeddingBackfillJob> {
+        let types = match &request.entity_types {
+            Some(types) => parse_entity_types(Some(&types.join(",")))?,
+            None => parse_entity_types(None)?,
+        };
+
+        let service = Self::new(db_pool)?;
+        let job = service.repo.create_job(&types, request.full_rebuild, "manual", created_by).await?;
+
+        let queued = job.clone();
+        tokio::spawn(async move {
+            service.run_job(&queued).await;
+        });
+
+        Ok(job)
+    }
+
+    pub async fn list_jobs(&self, limit: i64) -> Result<Vec<EmbeddingBackfillJob>> {
+        self.repo.list_jobs(limit.clamp(1, 100)).await
+    }
+
+    /// Run a queued job to completion, recording its outcome, then maintain the index
+    pub async fn run_job(&self, job: &EmbeddingBackfillJob) {
+        tracing::info!("Embedding backfill {} started ({})", job.id, job.entity_types.join(", "));
+
+        let outcome = self.backfill(job).await;
+        let error = outcome.as_ref().err().map(|e| e.to_string());
+
+        if let Err(e) = self.repo.finish_job(job.id, error.as_deref()).await {
+            tracing::error!("Failed to record the outcome of embedding backfill {}: {}", job.id, e);
+        }
+
+        match error {
+            None => tracing::info!("Embedding backfill {} completed", job.id),
+            Some(e) => tracing::error!("Embedding backfill {} failed: {}", job.id, e),
+        }
+
+        if let Err(e) = self.maintain_index().await {
+            tracing::error!("Semantic search index maintenance failed: {}", e);
+        }
+    }
+
+    async fn backfill(&self, job: &EmbeddingBackfillJob) -> Result<()> {
+        self.repo.mark_job_running(job.id).await?;
+
+        for entity_type in &job.entity_types {
+            let mut after = None;
+
+            loop {
+                let documents = self.repo.documents(entity_type, after, BACKFILL_BATCH_SIZE).await?;
+                let Some(last) = documents.last() else { break };
+                after = Some(last.entity_id);
+
+                let scanned = documents.len() as i32;
+                let embedded = self.embed_batch(entity_type, documents, job.full_rebuild).await?;
+                self.repo.add_job_progress(job.id, scanned, embedded as i32, 0).await?;
+            }
+
+            let removed = self.repo.remove_orphans(entity_type).await?;
+            self.repo.add_job_progress(job.id, 0, 0, removed as i32).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Embed the new and changed records of a batch (all of them on a full rebuild)
+    async fn embed_batch(&self, entity_type: &str, documents: Vec<SearchDocument>, full_rebuild: bool) -> Result<usize> {
+        let ids: Vec<Uuid> = documents.iter().map(|d| d.entity_id).collect();
+        let existing: HashMap<Uuid, String> = if full_rebuild {
+            HashMap::new()
+        } else {
+            self.repo.content_hashes(entity_type, &ids).await?.into_iter().collect()
+        };
+
+        let changed: Vec<(SearchDocument, String)> = documents
+            .into_iter()
+            .map(|document| {
+                let hash = document.content_hash();
+                (document, hash)
+            })
+            .filter(|(document, hash)| existing.get(&document.entity_id) != Some(hash))
+            .collect();
+
+        if changed.is_empty() {
+            return Ok(0);
+        }
+
+        let texts = changed.iter().map(|(document, _)| document.content.clone()).collect();
+        let vectors = self.embeddings.generate_embeddings(texts).await?;
+
+        let embedded: Vec<_> = changed
+            .into_iter()
+            .zip(vectors)
+            .map(|((document, hash), vector)| (document, hash, vector))
+            .collect();
+
+        self.repo.upsert_embeddings(entity_type, &embedded).await?;
+        Ok(embedded.len())
+    }
+
+    /// Rebuild the IVFFlat index once its list count no longer suits the table size
+    pub async fn maintain_index(&self) -> Result<()> {
+        let rows = self.repo.embedding_count().await?;
+        let target = ivfflat_lists(rows);
+        let current = self.repo.vector_index_lists().await?;
+
+        if ivfflat_needs_rebuild(current, target) {
+            tracing::info!(
+                "Rebuilding semantic search index: {} embeddings, lists {:?} -> {}",
+                rows, current, target
+            );
+            self.repo.rebuild_vector_index(target).await?;
+        }
+
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Semantic Index Scheduler
+// ============================================================================
+
+/// Embeds new and changed records on a fixed interval (incremental backfill)
+pub struct SemanticIndexScheduler {
+    pool: PgPool,
+    interval_minutes: u64,
+}
+
+impl SemanticIndexScheduler {
+    pub fn new(pool: PgPool) -> Self {
+        let interval_minutes = std::env::var("SEMANTIC_INDEX_INTERVAL_MINUTES")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .filter(|m| *m > 0)
+            .unwrap_or(60);
+
+        Self { pool, interval_minutes }
+    }
+
+    /// Run the scheduler loop
+    pub async fn run(&self) {
+        let service = match SemanticSearchService::new(self.pool.clone()) {
+            Ok(service) => service,
+            Err(e) => {
+                tracing::error!("Semantic index scheduler cannot start: {}", e);
+                return;
+            }
+        };
+
+        match service.repo.fail_interrupted_jobs().await {
+            Ok(0) => {}
+            Ok(failed) => tracing::warn!("Marked {} interrupted embedding backfill job(s) as failed", failed),
+            Err(e) => tracing::error!("Failed to clean up interrupted embedding backfill jobs: {}", e),
+        }
+
+        let mut ticker = tokio::time::interval(std::time::Duration::from_secs(self.interval_minutes * 60));
+
+        tracing::info!("Semantic index scheduler started - embedding changes every {} minutes", self.interval_minutes);
+
+        loop {
+            let deadline = ticker.tick().await;
+            crate::middleware::metrics::record_scheduler_lag("semantic_index", deadline);
+            if crate::services::MaintenanceService::pauses("semantic_index")
+                || !crate::services::SchedulerLeader::leads("semantic_index").await
+            {
+                continue;
+            }
+
+            let types: Vec<String> = SEMANTIC_ENTITY_TYPES.iter().map(|t| t.to_string()).collect();
+            match service.repo.create_job(&types, false, "scheduler", None).await {
+                Ok(job) => service.run_job(&job).await,
+                Err(AppError::Conflict) => tracing::info!("Embedding backfill already running, skipping scheduled run"),
+                Err(e) => tracing::error!("Failed to queue scheduled embedding backfill: {}", e),
+            }
+        }
+    }
+}
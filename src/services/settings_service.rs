@@ -0,0 +1,313 @@
+/// Settings Service
+///
+/// Runtime settings are held in a process-wide cache with typed accessors
+/// (`RuntimeSettings`), so hot paths such as CORS checks never touch the database.
+/// `reload` reads the overrides from `settings` into the cache and pushes the values
+/// that live elsewhere (rate limiter configs) to their owners. `run_listener` (started
+/// in main) reloads whenever the settings trigger announces a change on
+/// `SETTINGS_CHANNEL`, so a change made on any instance reaches every instance.
+
+use once_cell::sync::Lazy;
+use serde_json::Value;
+use sqlx::{postgres::PgListener, PgPool};
+use std::{
+    collections::HashMap,
+    sync::RwLock,
+    time::Duration,
+};
+use uuid::Uuid;
+use crate::{
+    middleware::{
+        error_handling::{AppError, Result},
+        ip_rate_limiter::{RateLimitConfig, RateLimiter},
+    },
+    models::settings::*,
+};
+
+/// Effective value of every known setting
+static SETTINGS: Lazy<RwLock<HashMap<&'static str, Value>>> = Lazy::new(|| RwLock::new(effective_values(&[])));
+
+/// Defaults overlaid with the overrides that are still valid
+fn effective_values(overrides: &[SettingOverride]) -> HashMap<&'static str, Value> {
+    SETTING_DEFINITIONS
+        .iter()
+        .map(|definition| {
+            let value = overrides
+                .iter()
+                .find(|row| row.key == definition.key)
+                .and_then(|row| match definition.validate(&row.value) {
+                    Ok(value) => Some(value),
+                    Err(e) => {
+                        tracing::warn!("Ignoring invalid setting override: {}", e);
+                        None
+                    }
+                })
+                .unwrap_or_else(|| definition.default_value());
+            (definition.key, value)
+        })
+        .collect()
+}
+
+/// Typed, cached access to runtime settings
+pub struct RuntimeSettings;
+
+impl RuntimeSettings {
+    pub fn get(key: &str) -> Value {
+        SETTINGS
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(key)
+            .cloned()
+            .unwrap_or(Value::Null)
+    }
+
+    fn int(key: &str) -> i64 {
+        Self::get(key)
+            .as_i64()
+            .or_else(|| SettingDefinition::find(key).and_then(|d| d.default_value().as_i64()))
+            .unwrap_or(1)
+    }
+
+    pub fn auth_rate_limit() -> RateLimitConfig {
+        RateLimitConfig {
+            max_requests: Self::int(RATE_LIMIT_AUTH_MAX_REQUESTS) as u32,
+            window: Duration::from_secs(Self::int(RATE_LIMIT_AUTH_WINDOW_SECONDS) as u64),
+        }
+    }
+
+    pub fn api_rate_limit() -> RateLimitConfig {
+        RateLimitConfig {
+            max_requests: Self::int(RATE_LIMIT_API_MAX_REQUESTS) as u32,
+            window: Duration::from_secs(Self::int(RATE_LIMIT_API_WINDOW_SECONDS) as u64),
+        }
+    }
+
+    pub fn erp_sync_poll_minutes() -> u64 {
+        Self::int(ERP_SYNC_POLL_MINUTES) as u64
+    }
+
+    pub fn erp_sync_max_concurrent() -> usize {
+        Self::int(ERP_SYNC_MAX_CONCURRENT) as usize
+    }
+
+    pub fn ai_max_topup_amount() -> i64 {
+        Self::int(AI_QUOTA_MAX_TOPUP_AMOUNT)
+    }
+
+    pub fn cors_origins() -> Vec<String> {
+        Self::get(CORS_ALLOWED_ORIGINS)
+            .as_array()
+            .map(|origins| origins.iter().filter_map(|o| o.as_str().map(str::to_string)).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn maintenance_enabled() -> bool {
+        Self::get(MAINTENANCE_ENABLED).as_bool().unwrap_or(false)
+    }
+
+    pub fn maintenance_retry_after_seconds() -> u64 {
+        Self::int(MAINTENANCE_RETRY_AFTER_SECONDS) as u64
+    }
+
+    /// None when auto-rejection is disabled
+    pub fn verification_auto_reject_days() -> Option<i64> {
+        Some(Self::int(VERIFICATION_AUTO_REJECT_DAYS)).filter(|days| *days > 0)
+    }
+
+    pub fn maintenance_message() -> String {
+        match Self::get(MAINTENANCE_MESSAGE) {
+            Value::String(message) => message,
+            _ => SettingDefinition::find(MAINTENANCE_MESSAGE)
+                .map(|d| d.default.to_string())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Whether a request's `Origin` header is allowed
+    pub fn is_cors_origin_allowed(origin: &[u8]) -> bool {
+        let settings = SETTINGS.read().unwrap_or_else(|e| e.into_inner());
+        settings
+            .get(CORS_ALLOWED_ORIGINS)
+            .and_then(Value::as_array)
+            .map(|origins| origins.iter().any(|allowed| allowed.as_str().map(str::as_bytes) == Some(origin)))
+            .unwrap_or(false)
+    }
+}
+
+pub struct SettingsService {
+    db_pool: PgPool,
+}
+
+impl SettingsService {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self { db_pool }
+    }
+
+    async fn overrides(&self) -> Result<Vec<SettingOverride>> {
+        let rows = sqlx::query_as::<_, SettingOverride>("SELECT * FROM settings ORDER BY key")
+            .fetch_all(&self.db_pool)
+            .await?;
+
+        Ok(rows)
+    }
+
+    /// Load the overrides into the cache and apply them
+    pub async fn reload(&self) -> Result<()> {
+        let values = effective_values(&self.overrides().await?);
+        *SETTINGS.write().unwrap_or_else(|e| e.into_inner()) = values;
+
+        RateLimiter::reconfigure("auth", RuntimeSettings::auth_rate_limit());
+        RateLimiter::reconfigure("api", RuntimeSettings::api_rate_limit());
+
+        Ok(())
+    }
+
+    /// Every known setting with its effective value
+    pub async fn list(&self) -> Result<Vec<SettingView>> {
+        let overrides = self.overrides().await?;
+        Ok(SETTING_DEFINITIONS
+            .iter()
+            .map(|definition| SettingView::new(definition, overrides.iter().find(|row| row.key == definition.key)))
+            .collect())
+    }
+
+    fn definition(key: &str) -> Result<&'static SettingDefinition> {
+        SettingDefinition::find(key).ok_or_else(|| AppError::NotFound(format!("Unknown setting '{}'", key)))
+    }
+
+    /// Override a setting; takes effect on every instance once committed
+    pub async fn update(&self, key: &str, value: &Value, admin_id: Uuid) -> Result<SettingView> {
+        let definition = Self::definition(key)?;
+        let value = definition.validate(value).map_err(AppError::BadRequest)?;
+
+        let mut tx = self.db_pool.begin().await?;
+
+        let old_value: Option<Value> = sqlx::query_scalar("SELECT value FROM settings WHERE key = $1 FOR UPDATE")
+            .bind(key)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+        let row = sqlx::query_as::<_, SettingOverride>(
+            r#"
+            INSERT INTO settings (key, value, updated_by, updated_at)
+            VALUES ($1, $2, $3, NOW())
+            ON CONFLICT (key) DO UPDATE
+            SET value = EXCLUDED.value, updated_by = EXCLUDED.updated_by, updated_at = NOW()
+            RETURNING *
+            "#
+        )
+        .bind(key)
+        .bind(&value)
+        .bind(admin_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        self.record_change(&mut tx, key, old_value, Some(&value), admin_id).await?;
+        tx.commit().await?;
+
+        self.reload().await?;
+        Ok(SettingView::new(definition, Some(&row)))
+    }
+
+    /// Drop the override, restoring the default
+    pub async fn reset(&self, key: &str, admin_id: Uuid) -> Result<SettingView> {
+        let definition = Self::definition(key)?;
+
+        let mut tx = self.db_pool.begin().await?;
+
+        let old_value: Option<Value> = sqlx::query_scalar("DELETE FROM settings WHERE key = $1 RETURNING value")
+            .bind(key)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+        if old_value.is_some() {
+            self.record_change(&mut tx, key, old_value, None, admin_id).await?;
+        }
+        tx.commit().await?;
+
+        self.reload().await?;
+        Ok(SettingView::new(definition, None))
+    }
+
+    /// Changes of a setting, newest first
+    pub async fn history(&self, key: &str, limit: i64) -> Result<Vec<SettingChange>> {
+        Self::definition(key)?;
+
+        let changes = sqlx::query_as::<_, SettingChange>(
+            "SELECT * FROM settings_history WHERE key = $1 ORDER BY changed_at DESC LIMIT $2"
+        )
+        .bind(key)
+        .bind(limit)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(changes)
+    }
+
+    async fn record_change(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        key: &str,
+        old_value: Option<Value>,
+        new_value: Option<&Value>,
+        admin_id: Uuid,
+    ) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO settings_history (key, old_value, new_value, changed_by) VALUES ($1, $2, $3, $4)"
+        )
+        .bind(key)
+        .bind(old_value)
+        .bind(new_value)
+        .bind(admin_id)
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    /// LISTEN for setting changes and reload. Runs for the life of the process; after a
+    /// lost connection the cache is reloaded in full, since changes may have been missed.
+    pub async fn run_listener(self) {
+        loop {
+            let mut listener = match PgListener::connect_with(&self.db_pool).await {
+                Ok(listener) => listener,
+                Err(e) => {
+                    tracing::error!("❌ Settings listener connection failed: {}", e);
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    continue;
+                }
+            };
+            if let Err(e) = listener.listen(SETTINGS_CHANNEL).await {
+                tracing::error!("❌ Settings LISTEN failed: {}", e);
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                continue;
+            }
+
+            // Catch up on anything changed while not listening
+            if let Err(e) = self.reload().await {
+                tracing::error!("Failed to reload runtime settings: {:?}", e);
+            }
+
+            loop {
+                match listener.try_recv().await {
+                    Ok(Some(notification)) => {
+                        tracing::info!("⚙️  Runtime setting '{}' changed, reloading", notification.payload());
+                        if let Err(e) = self.reload().await {
+                            tracing::error!("Failed to reload runtime settings: {:?}", e);
+                        }
+                    }
+                    // Connection lost; reconnect and reload
+                    Ok(None) => {
+                        tracing::warn!("Settings listener connection lost, reconnecting");
+                        break;
+                    }
+                    Err(e) => {
+                        tracing::warn!("Settings listener failed, reconnecting: {}", e);
+                        break;
+                    }
+                }
+            }
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+    }
+}
@@ -0,0 +1,306 @@
+/// Shutdown Service - graceful shutdown and draining of in-flight work
+///
+/// On SIGTERM (or Ctrl+C) the instance shuts down in steps instead of dying mid-sync:
+/// - the server stops accepting connections and finishes the requests in flight;
+///   real-time streams end so clients reconnect to another instance
+/// - `Shutdown::requested()` turns true: schedulers skip their runs, the job worker
+///   claims no new jobs (see `MaintenanceService::pauses`), and catalog syncs persist
+///   their progress and stop at their next batch
+/// - `Shutdown::drain` waits for tracked work (background jobs, sync runs) to finish
+/// - work still running when the drain timeout ends is handed back: its sync logs are
+///   marked `interrupted` and its background jobs are queued again, without using up
+///   an attempt, so another instance picks them up right away
+///
+/// Runs that fail while the instance is shutting down are recorded as `interrupted`
+/// rather than `failed`: the failure is most likely the shutdown itself.
+///
+/// Configuration:
+/// - SHUTDOWN_DRAIN_TIMEOUT_SECONDS (default 25): budget for the whole shutdown, from
+///   the signal until the process exits. Keep it below the orchestrator's grace period
+///   (Kubernetes: terminationGracePeriodSeconds, 30 by default).
+
+use dashmap::DashMap;
+use once_cell::sync::{Lazy, OnceCell};
+use sqlx::PgPool;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+use uuid::Uuid;
+use crate::{
+    middleware::error_handling::{AppError, Result},
+    services::BackgroundJobService,
+};
+
+/// Error message of runs stopped by a shutdown
+pub const INTERRUPTED_MESSAGE: &str = "Interrupted by shutdown";
+
+const DEFAULT_DRAIN_TIMEOUT_SECONDS: u64 = 25;
+
+/// When the shutdown started
+static STARTED: OnceCell<Instant> = OnceCell::new();
+static STARTED_NOTIFY: Lazy<Notify> = Lazy::new(Notify::new);
+
+static IN_FLIGHT: Lazy<DashMap<u64, InFlight>> = Lazy::new(DashMap::new);
+static NEXT_IN_FLIGHT_ID: AtomicU64 = AtomicU64::new(0);
+static DRAINED_NOTIFY: Lazy<Notify> = Lazy::new(Notify::new);
+
+static DRAIN_TIMEOUT: Lazy<Duration> = Lazy::new(|| {
+    Duration::from_secs(
+        std::env::var("SHUTDOWN_DRAIN_TIMEOUT_SECONDS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_DRAIN_TIMEOUT_SECONDS),
+    )
+});
+
+/// Sync log tables whose runs are tracked during shutdown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncLogTable {
+    /// `openfda_sync_log` (drug catalog, device and recall syncs)
+    OpenFda,
+    Ema,
+    RegulatorCatalog,
+    Erp,
+    SanctionsList,
+}
+
+impl SyncLogTable {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SyncLogTable::OpenFda => "openfda_sync_log",
+            SyncLogTable::Ema => "ema_sync_log",
+            SyncLogTable::RegulatorCatalog => "catalog_sync_log",
+            SyncLogTable::Erp => "erp_sync_logs",
+            SyncLogTable::SanctionsList => "sanctions_list_syncs",
+        }
+    }
+
+    /// Mark a run that is still in progress as interrupted. Returns false when it had
+    /// already finished.
+    pub async fn mark_interrupted(&self, pool: &PgPool, log_id: Uuid) -> Result<bool> {
+        let sql = match self {
+            SyncLogTable::OpenFda | SyncLogTable::Ema | SyncLogTable::RegulatorCatalog => format!(
+                r#"
+                UPDATE {}
+                SET status = 'interrupted', error_message = $2, sync_completed_at = NOW()
+                WHERE id = $1 AND status = 'in_progress'
+                "#,
+                self.as_str()
+            ),
+            SyncLogTable::Erp | SyncLogTable::SanctionsList => format!(
+                r#"
+                UPDATE {}
+                SET status = 'interrupted', error_message = $2, completed_at = NOW()
+                WHERE id = $1 AND status = 'running'
+                "#,
+                self.as_str()
+            ),
+        };
+
+        let mut tx = pool.begin().await?;
+        let marked = sqlx::query(&sql)
+            .bind(log_id)
+            .bind(INTERRUPTED_MESSAGE)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected()
+            > 0;
+
+        // Release the connection's sync claim so the next run doesn't wait for it to go stale
+        if marked && *self == SyncLogTable::Erp {
+            sqlx::query(
+                r#"
+                UPDATE erp_connections
+                SET last_sync_status = 'interrupted', sync_started_at = NULL, updated_at = NOW()
+                WHERE id = (SELECT erp_connection_id FROM erp_sync_logs WHERE id = $1)
+                  AND last_sync_status = 'running'
+                "#,
+            )
+            .bind(log_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(marked)
+    }
+}
+
+/// Work the drain waits for
+#[derive(Debug, Clone)]
+pub enum InFlight {
+    /// A background job holding a lease of this instance
+    Job { job_id: Uuid, lease_owner: String },
+    Sync { table: SyncLogTable, log_id: Uuid },
+}
+
+/// Tracks work for the drain until dropped
+#[must_use = "the work is only tracked while the guard is alive"]
+pub struct InFlightGuard(u64);
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        IN_FLIGHT.remove(&self.0);
+        if IN_FLIGHT.is_empty() {
+            DRAINED_NOTIFY.notify_waiters();
+        }
+    }
+}
+
+pub struct Shutdown;
+
+impl Shutdown {
+    /// Wait for SIGTERM or Ctrl+C, then start the shutdown
+    pub async fn listen() {
+        let ctrl_c = async {
+            if let Err(e) = tokio::signal::ctrl_c().await {
+                tracing::error!("Failed to listen for Ctrl+C: {}", e);
+                std::future::pending::<()>().await;
+            }
+        };
+
+        #[cfg(unix)]
+        let terminate = async {
+            match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+                Ok(mut signal) => {
+                    signal.recv().await;
+                }
+                Err(e) => {
+                    tracing::error!("Failed to listen for SIGTERM: {}", e);
+                    std::future::pending::<()>().await;
+                }
+            }
+        };
+        #[cfg(not(unix))]
+        let terminate = std::future::pending::<()>();
+
+        let signal = tokio::select! {
+            _ = ctrl_c => "Ctrl+C",
+            _ = terminate => "SIGTERM",
+        };
+        Self::begin(signal);
+    }
+
+    /// Start the shutdown (idempotent)
+    pub fn begin(reason: &str) {
+        if STARTED.set(Instant::now()).is_ok() {
+            tracing::warn!(
+                "🛑 {} received: shutting down, draining in-flight work for up to {}s",
+                reason,
+                DRAIN_TIMEOUT.as_secs()
+            );
+            STARTED_NOTIFY.notify_waiters();
+        }
+    }
+
+    /// Whether the instance is shutting down; long-running work checks this between batches
+    pub fn requested() -> bool {
+        STARTED.get().is_some()
+    }
+
+    /// Resolves once the shutdown has started
+    pub async fn wait() {
+        loop {
+            // Created before the check so a shutdown starting in between isn't missed
+            let started = STARTED_NOTIFY.notified();
+            if Self::requested() {
+                return;
+            }
+            started.await;
+        }
+    }
+
+    /// Time left of the drain timeout (the full timeout before the shutdown started)
+    pub fn remaining() -> Duration {
+        match STARTED.get() {
+            Some(started) => DRAIN_TIMEOUT.saturating_sub(started.elapsed()),
+            None => *DRAIN_TIMEOUT,
+        }
+    }
+
+    /// Resolves when the drain timeout has run out
+    pub async fn deadline() {
+        Self::wait().await;
+        tokio::time::sleep(Self::remaining()).await;
+    }
+
+    /// Error for a run that stopped early because the instance is shutting down
+    pub fn interrupted() -> AppError {
+        AppError::Internal(anyhow::anyhow!(INTERRUPTED_MESSAGE))
+    }
+
+    pub fn track(work: InFlight) -> InFlightGuard {
+        let id = NEXT_IN_FLIGHT_ID.fetch_add(1, Ordering::Relaxed);
+        IN_FLIGHT.insert(id, work);
+        InFlightGuard(id)
+    }
+
+    pub fn track_sync(table: SyncLogTable, log_id: Uuid) -> InFlightGuard {
+        Self::track(InFlight::Sync { table, log_id })
+    }
+
+    /// Wait for tracked work until the drain timeout, then hand back what is still
+    /// running. Returns the number of runs handed back.
+    pub async fn drain(pool: &PgPool) -> usize {
+        let drained = tokio::time::timeout(Self::remaining(), async {
+            loop {
+                let drained = DRAINED_NOTIFY.notified();
+                if IN_FLIGHT.is_empty() {
+                    return;
+                }
+                tracing::info!("⏳ Waiting for {} in-flight run(s) to finish", IN_FLIGHT.len());
+                drained.await;
+            }
+        })
+        .await;
+
+        if drained.is_ok() {
+            tracing::info!("✅ In-flight work drained");
+            return 0;
+        }
+
+        let remaining: Vec<InFlight> = IN_FLIGHT.iter().map(|entry| entry.value().clone()).collect();
+        tracing::warn!(
+            "⏱️  Drain timeout reached with {} run(s) in flight, handing them back",
+            remaining.len()
+        );
+
+        // Sync logs first: a job queued again may be claimed by another instance right away
+        let (syncs, jobs): (Vec<_>, Vec<_>) =
+            remaining.iter().partition(|work| matches!(work, InFlight::Sync { .. }));
+        let queue = BackgroundJobService::new(pool.clone());
+
+        for work in syncs.into_iter().chain(jobs) {
+            let result = match work {
+                InFlight::Sync { table, log_id } => table.mark_interrupted(pool, *log_id).await,
+                InFlight::Job { job_id, lease_owner } => queue.release(*job_id, lease_owner).await.map(|_| true),
+            };
+            if let Err(e) = result {
+                tracing::error!("Failed to hand back {:?}: {}", work, e);
+            }
+        }
+
+        remaining.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_flight_guard_stops_tracking_on_drop() {
+        let log_id = Uuid::new_v4();
+        let tracked = |id: Uuid| {
+            IN_FLIGHT
+                .iter()
+                .any(|entry| matches!(entry.value(), InFlight::Sync { log_id, .. } if *log_id == id))
+        };
+
+        let guard = Shutdown::track_sync(SyncLogTable::Ema, log_id);
+        assert!(tracked(log_id));
+
+        drop(guard);
+        assert!(!tracked(log_id));
+    }
+}
@@ -0,0 +1,166 @@
+/// Summary Aggregate Service
+///
+/// Admin stats, the manufacturer/category lists and marketplace facet counts read the
+/// summaries of migration 092 instead of scanning their tables per request:
+/// - `summary_counts` are kept current by triggers; `recount` corrects any drift
+/// - the materialized views are marked stale by writes to their source tables;
+///   `refresh_stale` claims the stale ones (so one instance refreshes each) and
+///   rebuilds them without blocking readers
+///
+/// `SummaryRefreshScheduler` runs `refresh_stale` every SUMMARY_REFRESH_INTERVAL_SECONDS
+/// (default 60) and `recount` daily. Responses built from a view carry its refresh time.
+
+use chrono::Utc;
+use sqlx::PgPool;
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+use crate::{
+    middleware::error_handling::Result,
+    models::summary_aggregate::{Summarized, SummaryView},
+};
+
+const DEFAULT_REFRESH_INTERVAL_SECONDS: u64 = 60;
+const RECOUNT_INTERVAL: Duration = Duration::from_secs(24 * 3600);
+
+/// Facet field -> value -> listings
+pub type FacetCounts = BTreeMap<String, BTreeMap<String, u64>>;
+
+#[derive(Clone)]
+pub struct SummaryAggregateService {
+    db_pool: PgPool,
+}
+
+impl SummaryAggregateService {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self { db_pool }
+    }
+
+    /// Refresh every view marked stale; returns the refreshed views
+    pub async fn refresh_stale(&self) -> Result<Vec<SummaryView>> {
+        // Writes during the refresh mark the view stale again for the next run
+        let claimed: Vec<String> = sqlx::query_scalar(
+            "UPDATE summary_refreshes SET stale_since = NULL WHERE stale_since IS NOT NULL RETURNING view_name",
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let mut refreshed = Vec::new();
+        for view in claimed.iter().filter_map(|name| SummaryView::parse(name)) {
+            match self.refresh(view).await {
+                Ok(()) => refreshed.push(view),
+                Err(e) => tracing::error!("Refresh of summary view {} failed: {:?}", view.view_name(), e),
+            }
+        }
+        Ok(refreshed)
+    }
+
+    /// Rebuild a view; a failed refresh leaves it marked stale
+    pub async fn refresh(&self, view: SummaryView) -> Result<()> {
+        let started_at = Utc::now();
+        let started = Instant::now();
+
+        let result = async {
+            let mut tx = self.db_pool.begin().await?;
+            // Catalog views can outlast the pool's statement timeout
+            sqlx::query("SET LOCAL statement_timeout = '10min'").execute(&mut *tx).await?;
+            sqlx::query(&format!("REFRESH MATERIALIZED VIEW CONCURRENTLY {}", view.view_name()))
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await
+        }
+        .await;
+
+        if let Err(e) = result {
+            sqlx::query("UPDATE summary_refreshes SET stale_since = COALESCE(stale_since, NOW()) WHERE view_name = $1")
+                .bind(view.view_name())
+                .execute(&self.db_pool)
+                .await?;
+            return Err(e.into());
+        }
+
+        sqlx::query("UPDATE summary_refreshes SET refreshed_at = $2, duration_ms = $3 WHERE view_name = $1")
+            .bind(view.view_name())
+            .bind(started_at)
+            .bind(started.elapsed().as_millis() as i64)
+            .execute(&self.db_pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Recount `summary_counts`; returns how many counters had drifted
+    pub async fn recount(&self) -> Result<i32> {
+        Ok(sqlx::query_scalar("SELECT refresh_summary_counts()").fetch_one(&self.db_pool).await?)
+    }
+
+    /// Facet counts of every listing on the marketplace, for browsing without a search
+    /// engine (the counts ignore search terms and filters)
+    pub async fn marketplace_facets(&self, facets: &[&str]) -> Result<Summarized<FacetCounts>> {
+        let rows: Vec<(Option<String>, Option<String>, Option<i64>, chrono::DateTime<Utc>)> = sqlx::query_as(
+            r#"
+            SELECT f.facet, f.value, f.listing_count, r.refreshed_at
+            FROM summary_refreshes r
+            LEFT JOIN marketplace_facet_counts f ON f.facet = ANY($1)
+            WHERE r.view_name = 'marketplace_facet_counts'
+            "#,
+        )
+        .bind(facets)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let mut counts: FacetCounts = facets.iter().map(|facet| (facet.to_string(), BTreeMap::new())).collect();
+        let refreshed_at = rows.first().map(|row| row.3);
+        for (facet, value, count, _) in rows {
+            if let (Some(facet), Some(value), Some(count)) = (facet, value, count) {
+                counts.entry(facet).or_default().insert(value, count as u64);
+            }
+        }
+        Ok(Summarized { value: counts, refreshed_at })
+    }
+}
+
+/// Refreshes stale summary views, and recounts the counters daily
+pub struct SummaryRefreshScheduler {
+    service: SummaryAggregateService,
+    interval: Duration,
+}
+
+impl SummaryRefreshScheduler {
+    pub fn new(pool: PgPool) -> Self {
+        let interval = std::env::var("SUMMARY_REFRESH_INTERVAL_SECONDS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|seconds| *seconds > 0)
+            .unwrap_or(DEFAULT_REFRESH_INTERVAL_SECONDS);
+
+        Self { service: SummaryAggregateService::new(pool), interval: Duration::from_secs(interval) }
+    }
+
+    pub async fn run(&self) {
+        let mut ticker = tokio::time::interval(self.interval);
+        let mut last_recount: Option<Instant> = None;
+
+        loop {
+            ticker.tick().await;
+            if crate::services::MaintenanceService::pauses("summary_refresh")
+                || !crate::services::SchedulerLeader::leads("summary_refresh").await
+            {
+                continue;
+            }
+
+            match self.service.refresh_stale().await {
+                Ok(views) if views.is_empty() => {}
+                Ok(views) => tracing::debug!("Refreshed summary views: {:?}", views),
+                Err(e) => tracing::error!("Summary view refresh failed: {:?}", e),
+            }
+
+            if last_recount.map(|at| at.elapsed() >= RECOUNT_INTERVAL).unwrap_or(true) {
+                match self.service.recount().await {
+                    Ok(0) => {}
+                    Ok(corrected) => tracing::warn!("Corrected {} drifted summary counters", corrected),
+                    Err(e) => tracing::error!("Summary counter recount failed: {:?}", e),
+                }
+                last_recount = Some(Instant::now());
+            }
+        }
+    }
+}
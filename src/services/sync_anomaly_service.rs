@@ -0,0 +1,107 @@
+/// Sync Anomaly Service
+///
+/// Guardrails for completed catalog syncs. Each sync service checks its run with
+/// `assess`, marks suspect runs on its own sync log, then calls `report`, which
+/// updates the Prometheus sync metrics, queues `sync.finished` webhooks and notifies
+/// every admin of suspect runs.
+
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::{
+    middleware::{error_handling::Result, metrics::record_catalog_sync},
+    models::{alerts::AlertPayload, sync_anomaly::*, webhook::WebhookEventType},
+    services::{NotificationService, OutboundWebhookService},
+};
+
+pub struct SyncAnomalyService {
+    db_pool: PgPool,
+    thresholds: SyncAnomalyThresholds,
+}
+
+impl SyncAnomalyService {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self {
+            db_pool,
+            thresholds: SyncAnomalyThresholds::from_env(),
+        }
+    }
+
+    pub fn assess(&self, stats: &SyncRunStats) -> Vec<SyncAnomaly> {
+        assess_sync_run(stats, &self.thresholds)
+    }
+
+    /// Catalog change events (status changes, discontinuations, recalls) recorded by a run
+    pub async fn count_catalog_changes(&self, sync_log_id: Uuid) -> Result<i64> {
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM catalog_change_events WHERE sync_log_id = $1"
+        )
+        .bind(sync_log_id)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        Ok(count)
+    }
+
+    /// Record the run in metrics and alert admins when it was marked suspect.
+    /// Notification failures are logged; they never fail the sync itself.
+    pub async fn report(&self, source: &str, sync_log_id: Uuid, anomalies: &[SyncAnomaly]) {
+        let kinds: Vec<&str> = anomalies.iter().map(SyncAnomaly::kind).collect();
+        record_catalog_sync(source, &kinds);
+
+        OutboundWebhookService::emit_in_background(
+            self.db_pool.clone(),
+            None,
+            WebhookEventType::SyncFinished,
+            serde_json::json!({
+                "source": source,
+                "sync_log_id": sync_log_id,
+                "suspect": !anomalies.is_empty(),
+                "anomalies": anomalies,
+            }),
+        );
+
+        if anomalies.is_empty() {
+            return;
+        }
+
+        let descriptions: Vec<String> = anomalies.iter().map(SyncAnomaly::description).collect();
+        tracing::warn!(
+            "{} sync {} marked suspect: {}",
+            source, sync_log_id, descriptions.join("; ")
+        );
+
+        if let Err(e) = self.notify_admins(source, sync_log_id, anomalies, &descriptions).await {
+            tracing::error!("Failed to notify admins about suspect {} sync {}: {:?}", source, sync_log_id, e);
+        }
+    }
+
+    async fn notify_admins(
+        &self,
+        source: &str,
+        sync_log_id: Uuid,
+        anomalies: &[SyncAnomaly],
+        descriptions: &[String],
+    ) -> Result<()> {
+        let admin_ids: Vec<Uuid> = sqlx::query_scalar(
+            "SELECT id FROM users WHERE role IN ('admin', 'superadmin')"
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let notification_service = NotificationService::new(self.db_pool.clone());
+        let anomalies = serde_json::to_value(anomalies)?;
+
+        for admin_id in admin_ids {
+            let payload = AlertPayload::new_sync_anomaly(
+                admin_id,
+                source,
+                sync_log_id,
+                descriptions,
+                anomalies.clone(),
+            );
+            notification_service.create_alert(payload).await?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,171 @@
+/// Sync Source Service
+///
+/// Runtime settings of catalog sync sources (rate budget, burst limit, API key and
+/// scheduler pause), stored in `sync_source_settings` and editable by admins. Sync
+/// services read them when a run starts; schedulers check the pause flag each tick.
+/// Also builds the sync dashboard, the health summary of every sync pipeline.
+
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::{
+    middleware::error_handling::{AppError, Result},
+    models::{sync_dashboard::*, sync_source::*},
+    repositories::SyncSourceRepository,
+    services::EncryptionService,
+};
+
+/// Catalogs refresh weekly; a day of slack before a pipeline is reported stale
+const DEFAULT_STALE_AFTER_HOURS: i64 = 192;
+
+pub struct SyncSourceService {
+    repo: SyncSourceRepository,
+}
+
+impl SyncSourceService {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self { repo: SyncSourceRepository::new(db_pool) }
+    }
+
+    pub async fn list(&self) -> Result<Vec<SyncSourceSettingsResponse>> {
+        let settings = self.repo.list().await?;
+        Ok(settings.into_iter().map(Into::into).collect())
+    }
+
+    pub async fn update(
+        &self,
+        source: &str,
+        request: UpdateSyncSourceRequest,
+        admin_id: Uuid,
+    ) -> Result<SyncSourceSettingsResponse> {
+        let source = validate_source(source)?;
+        let current = self.repo.get(source).await?;
+
+        for value in [request.requests_per_minute, request.burst_limit].into_iter().flatten() {
+            if !(1..=100_000).contains(&value) {
+                return Err(AppError::InvalidInput(
+                    "requests_per_minute and burst_limit must be between 1 and 100000".to_string()
+                ));
+            }
+        }
+
+        let (requests_per_minute, burst_limit) = if request.clear_rate_limit {
+            (None, None)
+        } else {
+            (
+                request.requests_per_minute.or(current.as_ref().and_then(|c| c.requests_per_minute)),
+                request.burst_limit.or(current.as_ref().and_then(|c| c.burst_limit)),
+            )
+        };
+
+        let api_key_encrypted = match request.api_key.as_deref().map(str::trim) {
+            _ if request.clear_api_key => None,
+            Some(key) if !key.is_empty() => Some(encryption_service()?.encrypt(key)?),
+            _ => current.and_then(|c| c.api_key_encrypted),
+        };
+
+        let settings = self
+            .repo
+            .upsert_limits(source, requests_per_minute, burst_limit, api_key_encrypted.as_deref(), admin_id)
+            .await?;
+
+        tracing::info!(
+            "Admin {} updated {} sync settings: requests_per_minute={:?}, burst_limit={:?}, api_key={}",
+            admin_id, source, requests_per_minute, burst_limit,
+            if api_key_encrypted.is_some() { "set" } else { "none" }
+        );
+
+        Ok(settings.into())
+    }
+
+    pub async fn set_scheduler_paused(
+        &self,
+        source: &str,
+        paused: bool,
+        reason: Option<String>,
+        admin_id: Uuid,
+    ) -> Result<SyncSourceSettingsResponse> {
+        let source = validate_source(source)?;
+        let settings = self
+            .repo
+            .set_paused(source, paused, reason.as_deref().filter(|_| paused), admin_id)
+            .await?;
+
+        tracing::info!(
+            "Admin {} {} the {} sync scheduler",
+            admin_id, if paused { "paused" } else { "resumed" }, source
+        );
+
+        Ok(settings.into())
+    }
+
+    /// Settings for a sync run; missing rows and columns mean "use the environment"
+    pub async fn runtime_settings(&self, source: &str) -> Result<SourceRuntimeSettings> {
+        let settings = match self.repo.get(source).await? {
+            Some(settings) => settings,
+            None => return Ok(SourceRuntimeSettings::default()),
+        };
+
+        let api_key = match &settings.api_key_encrypted {
+            Some(encrypted) => Some(encryption_service()?.decrypt(encrypted)?),
+            None => None,
+        };
+
+        Ok(SourceRuntimeSettings {
+            requests_per_minute: settings.requests_per_minute.map(|v| v as u32),
+            burst_limit: settings.burst_limit.map(|v| v as u32),
+            api_key,
+            scheduler_paused: settings.scheduler_paused,
+        })
+    }
+
+    /// Health of every sync pipeline: latest run, recent failures and time since the
+    /// last successful sync (stale after SYNC_DASHBOARD_STALE_HOURS, default 8 days)
+    pub async fn dashboard(&self) -> Result<SyncDashboard> {
+        let stale_after_hours = std::env::var("SYNC_DASHBOARD_STALE_HOURS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_STALE_AFTER_HOURS);
+
+        let sources: Vec<String> = SYNC_SOURCES.iter().map(|s| s.to_string()).collect();
+        let now = chrono::Utc::now();
+        let pipelines = self
+            .repo
+            .pipeline_rows(&sources)
+            .await?
+            .into_iter()
+            .map(|row| assess_pipeline(row, now, stale_after_hours))
+            .collect();
+
+        Ok(SyncDashboard::new(pipelines, stale_after_hours))
+    }
+
+    /// Whether scheduled syncs of the source are paused. Errors are logged and treated
+    /// as not paused, so a settings problem never silently stops catalog refreshes.
+    pub async fn is_scheduler_paused(&self, source: &str) -> bool {
+        match self.repo.is_scheduler_paused(source).await {
+            Ok(paused) => paused,
+            Err(e) => {
+                tracing::error!("Failed to read {} scheduler pause flag: {:?}", source, e);
+                false
+            }
+        }
+    }
+}
+
+fn validate_source(source: &str) -> Result<&'static str> {
+    SYNC_SOURCES
+        .iter()
+        .find(|s| s.eq_ignore_ascii_case(source.trim()))
+        .copied()
+        .ok_or_else(|| AppError::NotFound(format!(
+            "Unknown sync source '{}'. Available: {}",
+            source,
+            SYNC_SOURCES.join(", ")
+        )))
+}
+
+fn encryption_service() -> Result<EncryptionService> {
+    let key = std::env::var("ENCRYPTION_KEY")
+        .map_err(|_| AppError::Internal(anyhow::anyhow!("ENCRYPTION_KEY not set")))?;
+    Ok(EncryptionService::new(&key)?)
+}
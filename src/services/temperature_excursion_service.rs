@@ -0,0 +1,501 @@
+/// Temperature Excursion Service (GDP)
+///
+/// Temperature logger data of shipments (marketplace transactions). Either party
+/// attaches a logger export; it is analysed against the product's storage conditions
+/// (parsed from its storage requirements, or a range given on upload) and every run of
+/// readings outside the range is stored as an excursion. The buyer can open a dispute
+/// from an excursion report, which the seller accepts or rejects (or the buyer
+/// withdraws). The summary aggregates a period for GDP self-inspections.
+
+use crate::{
+    middleware::error_handling::{AppError, Result},
+    models::alerts::AlertPayload,
+    models::temperature_excursion::*,
+    services::NotificationService,
+};
+use chrono::{Duration, NaiveDate, TimeZone, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+const LOG_COLUMNS: &str = r#"
+    id, transaction_id, uploaded_by, logger_serial, source_format, range_source,
+    min_allowed_c, max_allowed_c, reading_count, skipped_rows, first_reading_at,
+    last_reading_at, min_temp_c, max_temp_c, mean_temp_c, mean_kinetic_temp_c,
+    longest_gap_seconds, excursion_count, total_excursion_seconds, notes, created_at
+"#;
+
+pub struct TemperatureExcursionService {
+    db_pool: PgPool,
+}
+
+impl TemperatureExcursionService {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self { db_pool }
+    }
+
+    /// Attach logger data to a shipment the user is a party to and detect excursions
+    pub async fn upload(
+        &self,
+        transaction_id: Uuid,
+        user_id: Uuid,
+        request: UploadTemperatureLogRequest,
+    ) -> Result<ExcursionReport> {
+        let shipment = self.load_shipment(transaction_id, user_id).await?;
+
+        let (range, range_source) = match request.manual_range() {
+            Some(range) => (range, "manual"),
+            None => (
+                shipment
+                    .storage_requirements
+                    .as_deref()
+                    .and_then(parse_storage_range)
+                    .ok_or_else(|| {
+                        AppError::BadRequest(
+                            "The product's storage requirements state no temperature range; give min_temp_c or max_temp_c"
+                                .to_string(),
+                        )
+                    })?,
+                "product",
+            ),
+        };
+        if let (Some(min), Some(max)) = (range.min_c, range.max_c) {
+            if min > max {
+                return Err(AppError::BadRequest("min_temp_c must not be above max_temp_c".to_string()));
+            }
+        }
+
+        let parsed = parse_logger_data(request.format, &request.data, request.utc_offset_minutes)
+            .map_err(AppError::BadRequest)?;
+        let analysis = analyze_readings(&parsed.readings, &range);
+
+        let mut tx = self.db_pool.begin().await?;
+        let log_id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO shipment_temperature_logs (
+                transaction_id, uploaded_by, logger_serial, source_format, range_source,
+                min_allowed_c, max_allowed_c, reading_count, skipped_rows, first_reading_at,
+                last_reading_at, min_temp_c, max_temp_c, mean_temp_c, mean_kinetic_temp_c,
+                longest_gap_seconds, excursion_count, total_excursion_seconds, readings, notes
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20)
+            RETURNING id
+            "#,
+        )
+        .bind(transaction_id)
+        .bind(user_id)
+        .bind(request.logger_serial.as_deref().map(str::trim))
+        .bind(request.format.as_str())
+        .bind(range_source)
+        .bind(range.min_c)
+        .bind(range.max_c)
+        .bind(analysis.reading_count)
+        .bind(parsed.skipped_rows as i32)
+        .bind(analysis.first_reading_at)
+        .bind(analysis.last_reading_at)
+        .bind(analysis.min_temp_c)
+        .bind(analysis.max_temp_c)
+        .bind(analysis.mean_temp_c)
+        .bind(analysis.mean_kinetic_temp_c)
+        .bind(analysis.longest_gap_seconds)
+        .bind(analysis.excursions.len() as i32)
+        .bind(analysis.total_excursion_seconds)
+        .bind(serde_json::to_value(&parsed.readings)?)
+        .bind(&request.notes)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        for excursion in &analysis.excursions {
+            sqlx::query(
+                r#"
+                INSERT INTO shipment_temperature_excursions (
+                    temperature_log_id, transaction_id, kind, started_at, ended_at, duration_seconds,
+                    reading_count, extreme_temp_c, deviation_c, ongoing
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                "#,
+            )
+            .bind(log_id)
+            .bind(transaction_id)
+            .bind(excursion.kind.as_str())
+            .bind(excursion.started_at)
+            .bind(excursion.ended_at)
+            .bind(excursion.duration_seconds)
+            .bind(excursion.reading_count)
+            .bind(excursion.extreme_temp_c)
+            .bind(excursion.deviation_c)
+            .bind(excursion.ongoing)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        tracing::info!(
+            "Audit: User {} attached temperature log {} to transaction {} ({} readings, {} excursions)",
+            user_id,
+            log_id,
+            transaction_id,
+            analysis.reading_count,
+            analysis.excursions.len()
+        );
+
+        self.build_report(shipment, log_id).await
+    }
+
+    /// Temperature logs of a shipment the user is a party to, newest first
+    pub async fn list_logs(&self, transaction_id: Uuid, user_id: Uuid) -> Result<Vec<TemperatureLog>> {
+        self.load_shipment(transaction_id, user_id).await?;
+
+        let logs = sqlx::query_as::<_, TemperatureLog>(&format!(
+            "SELECT {} FROM shipment_temperature_logs WHERE transaction_id = $1 ORDER BY created_at DESC",
+            LOG_COLUMNS
+        ))
+        .bind(transaction_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(logs)
+    }
+
+    /// Excursion report of one temperature log
+    pub async fn report(&self, transaction_id: Uuid, log_id: Uuid, user_id: Uuid) -> Result<ExcursionReport> {
+        let shipment = self.load_shipment(transaction_id, user_id).await?;
+        self.build_report(shipment, log_id).await
+    }
+
+    /// Buyer's dispute over the excursions of a temperature log. The report is kept as
+    /// evidence and the seller is notified.
+    pub async fn open_dispute(
+        &self,
+        transaction_id: Uuid,
+        log_id: Uuid,
+        user_id: Uuid,
+        request: OpenDisputeRequest,
+    ) -> Result<TransactionDispute> {
+        let shipment = self.load_shipment(transaction_id, user_id).await?;
+        if shipment.buyer_id != user_id {
+            return Err(AppError::Forbidden("Only the buyer can dispute a shipment".to_string()));
+        }
+
+        let report = self.build_report(shipment.clone(), log_id).await?;
+        if report.within_limits {
+            return Err(AppError::BadRequest(
+                "The temperature log has no excursions to dispute".to_string(),
+            ));
+        }
+
+        let dispute = sqlx::query_as::<_, TransactionDispute>(
+            r#"
+            INSERT INTO transaction_disputes
+                (transaction_id, raised_by, reason, temperature_log_id, description, evidence)
+            VALUES ($1, $2, 'temperature_excursion', $3, $4, $5)
+            RETURNING *
+            "#,
+        )
+        .bind(transaction_id)
+        .bind(user_id)
+        .bind(log_id)
+        .bind(request.description.trim())
+        .bind(serde_json::to_value(&report)?)
+        .fetch_one(&self.db_pool)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(ref db) if db.code().as_deref() == Some("23505") => AppError::Conflict,
+            other => AppError::Database(other),
+        })?;
+
+        tracing::info!(
+            "Audit: User {} opened dispute {} on transaction {} over temperature log {}",
+            user_id,
+            dispute.id,
+            transaction_id,
+            log_id
+        );
+
+        let buyer_company: Option<String> = sqlx::query_scalar("SELECT company_name FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_optional(&self.db_pool)
+            .await?;
+        self.notify(AlertPayload::new_dispute_opened(
+            shipment.seller_id,
+            user_id,
+            buyer_company.as_deref().unwrap_or("The buyer"),
+            transaction_id,
+            dispute.id,
+            &shipment.brand_name,
+            report.log.excursion_count,
+        ))
+        .await;
+
+        Ok(dispute)
+    }
+
+    /// Disputes of a shipment the user is a party to, newest first
+    pub async fn list_disputes(&self, transaction_id: Uuid, user_id: Uuid) -> Result<Vec<TransactionDispute>> {
+        self.load_shipment(transaction_id, user_id).await?;
+        self.disputes(transaction_id).await
+    }
+
+    /// The seller accepts or rejects an open dispute; the buyer can withdraw it
+    pub async fn resolve_dispute(
+        &self,
+        dispute_id: Uuid,
+        user_id: Uuid,
+        request: ResolveDisputeRequest,
+    ) -> Result<TransactionDispute> {
+        let transaction_id: Uuid = sqlx::query_scalar("SELECT transaction_id FROM transaction_disputes WHERE id = $1")
+            .bind(dispute_id)
+            .fetch_optional(&self.db_pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Dispute not found".to_string()))?;
+        let shipment = self.load_shipment(transaction_id, user_id).await?;
+
+        let (allowed, notify_user_id) = match request.outcome {
+            DisputeOutcome::Accepted | DisputeOutcome::Rejected => (shipment.seller_id == user_id, shipment.buyer_id),
+            DisputeOutcome::Withdrawn => (shipment.buyer_id == user_id, shipment.seller_id),
+        };
+        if !allowed {
+            return Err(AppError::Forbidden(match request.outcome {
+                DisputeOutcome::Withdrawn => "Only the buyer can withdraw a dispute".to_string(),
+                _ => "Only the seller can accept or reject a dispute".to_string(),
+            }));
+        }
+
+        let resolution = request.resolution.trim();
+        let dispute = sqlx::query_as::<_, TransactionDispute>(
+            r#"
+            UPDATE transaction_disputes
+            SET status = $2, resolution = $3, resolved_by = $4, resolved_at = NOW(), updated_at = NOW()
+            WHERE id = $1 AND status = 'open'
+            RETURNING *
+            "#,
+        )
+        .bind(dispute_id)
+        .bind(request.outcome.as_str())
+        .bind(resolution)
+        .bind(user_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::BadRequest("The dispute is no longer open".to_string()))?;
+
+        tracing::info!(
+            "Audit: User {} {} dispute {} on transaction {}",
+            user_id,
+            request.outcome.as_str(),
+            dispute_id,
+            transaction_id
+        );
+
+        self.notify(AlertPayload::new_dispute_resolved(
+            notify_user_id,
+            user_id,
+            transaction_id,
+            dispute_id,
+            request.outcome.as_str(),
+            resolution,
+        ))
+        .await;
+
+        Ok(dispute)
+    }
+
+    /// GDP self-inspection figures of the user's shipments over a period (by upload
+    /// date of the logs and opening date of the disputes)
+    pub async fn summary(&self, user_id: Uuid, query: TemperatureSummaryQuery) -> Result<TemperatureSummary> {
+        let to = query.to.unwrap_or_else(|| Utc::now().date_naive());
+        let from = query.from.unwrap_or(to - Duration::days(365));
+        if from > to {
+            return Err(AppError::BadRequest("from must not be after to".to_string()));
+        }
+        let start = day_start(from);
+        let end = day_start(to + Duration::days(1));
+
+        let (shipments_monitored, logs_uploaded, shipments_with_excursions) = sqlx::query_as::<_, (i64, i64, i64)>(
+            r#"
+            SELECT COUNT(DISTINCT l.transaction_id),
+                   COUNT(*),
+                   COUNT(DISTINCT l.transaction_id) FILTER (WHERE l.excursion_count > 0)
+            FROM shipment_temperature_logs l
+            JOIN transactions t ON t.id = l.transaction_id
+            WHERE (t.seller_id = $1 OR t.buyer_id = $1)
+              AND l.created_at >= $2 AND l.created_at < $3
+            "#,
+        )
+        .bind(user_id)
+        .bind(start)
+        .bind(end)
+        .fetch_one(&self.db_pool)
+        .await?;
+
+        let (excursion_count, excursions_above, excursions_below, total_excursion_seconds, longest_excursion_seconds) =
+            sqlx::query_as::<_, (i64, i64, i64, i64, i64)>(
+                r#"
+                SELECT COUNT(*),
+                       COUNT(*) FILTER (WHERE e.kind = 'above'),
+                       COUNT(*) FILTER (WHERE e.kind = 'below'),
+                       COALESCE(SUM(e.duration_seconds), 0)::BIGINT,
+                       COALESCE(MAX(e.duration_seconds), 0)
+                FROM shipment_temperature_excursions e
+                JOIN shipment_temperature_logs l ON l.id = e.temperature_log_id
+                JOIN transactions t ON t.id = e.transaction_id
+                WHERE (t.seller_id = $1 OR t.buyer_id = $1)
+                  AND l.created_at >= $2 AND l.created_at < $3
+                "#,
+            )
+            .bind(user_id)
+            .bind(start)
+            .bind(end)
+            .fetch_one(&self.db_pool)
+            .await?;
+
+        let (disputes_opened, disputes_open, disputes_accepted, disputes_rejected, disputes_withdrawn) =
+            sqlx::query_as::<_, (i64, i64, i64, i64, i64)>(
+                r#"
+                SELECT COUNT(*),
+                       COUNT(*) FILTER (WHERE d.status = 'open'),
+                       COUNT(*) FILTER (WHERE d.status = 'accepted'),
+                       COUNT(*) FILTER (WHERE d.status = 'rejected'),
+                       COUNT(*) FILTER (WHERE d.status = 'withdrawn')
+                FROM transaction_disputes d
+                JOIN transactions t ON t.id = d.transaction_id
+                WHERE (t.seller_id = $1 OR t.buyer_id = $1)
+                  AND d.reason = 'temperature_excursion'
+                  AND d.created_at >= $2 AND d.created_at < $3
+                "#,
+            )
+            .bind(user_id)
+            .bind(start)
+            .bind(end)
+            .fetch_one(&self.db_pool)
+            .await?;
+
+        let by_product = sqlx::query_as::<_, ProductExcursionSummary>(
+            r#"
+            SELECT p.id AS pharmaceutical_id, p.brand_name, p.generic_name,
+                   COUNT(DISTINCT l.transaction_id) AS shipments_monitored,
+                   COUNT(DISTINCT l.transaction_id) FILTER (WHERE l.excursion_count > 0) AS shipments_with_excursions,
+                   COALESCE(SUM(l.excursion_count), 0)::BIGINT AS excursion_count,
+                   COALESCE(SUM(l.total_excursion_seconds), 0)::BIGINT AS total_excursion_seconds
+            FROM shipment_temperature_logs l
+            JOIN transactions t ON t.id = l.transaction_id
+            JOIN inquiries q ON q.id = t.inquiry_id
+            JOIN inventory i ON i.id = q.inventory_id
+            JOIN pharmaceuticals p ON p.id = i.pharmaceutical_id
+            WHERE (t.seller_id = $1 OR t.buyer_id = $1)
+              AND l.created_at >= $2 AND l.created_at < $3
+            GROUP BY p.id, p.brand_name, p.generic_name
+            ORDER BY excursion_count DESC, shipments_monitored DESC
+            "#,
+        )
+        .bind(user_id)
+        .bind(start)
+        .bind(end)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(TemperatureSummary {
+            from,
+            to,
+            shipments_monitored,
+            logs_uploaded,
+            shipments_with_excursions,
+            excursion_count,
+            excursions_above,
+            excursions_below,
+            total_excursion_seconds,
+            longest_excursion_seconds,
+            disputes_opened,
+            disputes_open,
+            disputes_accepted,
+            disputes_rejected,
+            disputes_withdrawn,
+            by_product,
+        })
+    }
+
+    // ========================================================================
+    // PRIVATE HELPERS
+    // ========================================================================
+
+    /// Shipment with its product; the user has to be the seller or the buyer
+    async fn load_shipment(&self, transaction_id: Uuid, user_id: Uuid) -> Result<ShipmentProduct> {
+        let shipment = sqlx::query_as::<_, ShipmentProduct>(
+            r#"
+            SELECT t.id AS transaction_id, t.seller_id, t.buyer_id, p.id AS pharmaceutical_id,
+                   p.brand_name, p.generic_name, p.ndc_code, i.batch_number, t.quantity,
+                   p.storage_requirements
+            FROM transactions t
+            JOIN inquiries q ON q.id = t.inquiry_id
+            JOIN inventory i ON i.id = q.inventory_id
+            JOIN pharmaceuticals p ON p.id = i.pharmaceutical_id
+            WHERE t.id = $1
+            "#,
+        )
+        .bind(transaction_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Transaction not found".to_string()))?;
+
+        if shipment.seller_id != user_id && shipment.buyer_id != user_id {
+            return Err(AppError::Forbidden("Access denied".to_string()));
+        }
+        Ok(shipment)
+    }
+
+    async fn build_report(&self, shipment: ShipmentProduct, log_id: Uuid) -> Result<ExcursionReport> {
+        let log = sqlx::query_as::<_, TemperatureLog>(&format!(
+            "SELECT {} FROM shipment_temperature_logs WHERE id = $1 AND transaction_id = $2",
+            LOG_COLUMNS
+        ))
+        .bind(log_id)
+        .bind(shipment.transaction_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Temperature log not found".to_string()))?;
+
+        let excursions = sqlx::query_as::<_, TemperatureExcursion>(
+            "SELECT * FROM shipment_temperature_excursions WHERE temperature_log_id = $1 ORDER BY started_at",
+        )
+        .bind(log_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let disputes = self
+            .disputes(shipment.transaction_id)
+            .await?
+            .into_iter()
+            .filter(|d| d.temperature_log_id == Some(log_id))
+            .collect();
+
+        Ok(ExcursionReport {
+            within_limits: excursions.is_empty(),
+            shipment,
+            log,
+            excursions,
+            disputes,
+            generated_at: Utc::now(),
+        })
+    }
+
+    async fn disputes(&self, transaction_id: Uuid) -> Result<Vec<TransactionDispute>> {
+        let disputes = sqlx::query_as::<_, TransactionDispute>(
+            "SELECT * FROM transaction_disputes WHERE transaction_id = $1 ORDER BY created_at DESC",
+        )
+        .bind(transaction_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(disputes)
+    }
+
+    /// Notifications are best effort; the dispute itself is already stored
+    async fn notify(&self, payload: AlertPayload) {
+        let user_id = payload.user_id;
+        if let Err(e) = NotificationService::new(self.db_pool.clone()).create_alert(payload).await {
+            tracing::warn!("Could not notify user {} about a dispute: {:?}", user_id, e);
+        }
+    }
+}
+
+fn day_start(date: NaiveDate) -> chrono::DateTime<Utc> {
+    Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0).expect("valid midnight"))
+}
@@ -6,8 +6,12 @@
 /// - O(1) lookup performance using DashMap
 ///
 /// Industry standard: Redis-backed for distributed systems, in-memory for single instance
+///
+/// Whole accounts (e.g. suspended users) are revoked with a per-user cutoff: every token
+/// issued at or before it is rejected. Cutoffs are persisted in `users.sessions_revoked_at`
+/// and synced into each instance by the suspension scheduler.
 
-use dashmap::DashMap;
+use dashmap::{mapref::entry::Entry, DashMap};
 use std::{
     sync::Arc,
     time::{Duration, Instant},
@@ -33,17 +37,24 @@ struct BlacklistEntry {
 pub struct TokenBlacklistService {
     /// Map of token JTI (JWT ID) to blacklist entry
     blacklist: Arc<DashMap<String, BlacklistEntry>>,
+    /// User ID to unix time at or before which their tokens were issued to be rejected
+    session_cutoffs: Arc<DashMap<Uuid, i64>>,
 }
 
+/// Longest token lifetime (regular users); older cutoffs can't match a valid token
+const MAX_TOKEN_LIFETIME_SECS: i64 = 24 * 3600;
+
 impl TokenBlacklistService {
     /// Create new token blacklist service with automatic cleanup
     pub fn new() -> Self {
         let service = Self {
             blacklist: Arc::new(DashMap::new()),
+            session_cutoffs: Arc::new(DashMap::new()),
         };
 
         // Spawn background cleanup task
         let blacklist = service.blacklist.clone();
+        let session_cutoffs = service.session_cutoffs.clone();
         tokio::spawn(async move {
             loop {
                 sleep(Duration::from_secs(300)).await; // Cleanup every 5 minutes
@@ -57,6 +68,9 @@ impl TokenBlacklistService {
                     keep
                 });
 
+                let oldest_valid_iat = chrono::Utc::now().timestamp() - MAX_TOKEN_LIFETIME_SECS;
+                session_cutoffs.retain(|_, cutoff| *cutoff >= oldest_valid_iat);
+
                 tracing::info!("🧹 Token blacklist cleanup completed. Active entries: {}", blacklist.len());
             }
         });
@@ -132,6 +146,37 @@ impl TokenBlacklistService {
         );
     }
 
+    /// Reject every token of the user issued at or before `cutoff` (unix seconds)
+    ///
+    /// Returns false if an equal or later cutoff was already in place, so re-syncing
+    /// persisted cutoffs is cheap and quiet.
+    pub fn revoke_sessions_before(&self, user_id: Uuid, cutoff: i64, reason: &str) -> bool {
+        let changed = match self.session_cutoffs.entry(user_id) {
+            Entry::Occupied(mut current) if cutoff > *current.get() => {
+                current.insert(cutoff);
+                true
+            }
+            Entry::Occupied(_) => false,
+            Entry::Vacant(entry) => {
+                entry.insert(cutoff);
+                true
+            }
+        };
+
+        if changed {
+            tracing::warn!("🚫 Revoked sessions of user {} issued up to {} (reason: {})", user_id, cutoff, reason);
+        }
+        changed
+    }
+
+    /// Check if the user's sessions issued at `issued_at` (JWT iat) were revoked
+    pub fn is_session_revoked(&self, user_id: Uuid, issued_at: usize) -> bool {
+        self.session_cutoffs
+            .get(&user_id)
+            .map(|cutoff| issued_at as i64 <= *cutoff)
+            .unwrap_or(false)
+    }
+
     /// Get statistics for monitoring
     pub fn stats(&self) -> BlacklistStats {
         let now = Instant::now();
@@ -215,6 +260,26 @@ mod tests {
         assert!(!service.is_blacklisted(&jti2));
     }
 
+    #[tokio::test]
+    async fn test_session_cutoff() {
+        let service = TokenBlacklistService::new();
+        let user_id = Uuid::new_v4();
+        let now = chrono::Utc::now().timestamp();
+
+        assert!(!service.is_session_revoked(user_id, now as usize));
+        assert!(service.revoke_sessions_before(user_id, now, "suspended"));
+
+        // Tokens issued up to the cutoff are rejected; later ones (after reinstatement) pass
+        assert!(service.is_session_revoked(user_id, (now - 60) as usize));
+        assert!(service.is_session_revoked(user_id, now as usize));
+        assert!(!service.is_session_revoked(user_id, (now + 1) as usize));
+        assert!(!service.is_session_revoked(Uuid::new_v4(), (now - 60) as usize));
+
+        // Cutoffs only move forward
+        assert!(!service.revoke_sessions_before(user_id, now - 3600, "sync"));
+        assert!(service.is_session_revoked(user_id, now as usize));
+    }
+
     #[tokio::test]
     async fn test_stats() {
         let service = TokenBlacklistService::new();
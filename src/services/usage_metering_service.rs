@@ -0,0 +1,378 @@
+/// Usage Metering Service
+///
+/// Records billable usage per account into `usage_daily` and prices it on the account's
+/// billing plan:
+/// - counters (transactions, AI calls) are recorded where the usage happens
+/// - API calls are counted in memory by the auth middleware and flushed every minute,
+///   so authenticated requests don't each write a row
+/// - gauges (listings, ERP connections) are sampled hourly from their tables
+/// - statements for a month are generated once it has ended
+///
+/// Metering never fails the request it meters; recording errors are logged.
+
+use chrono::{Duration as ChronoDuration, Months, NaiveDate, Utc};
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use sqlx::PgPool;
+use std::time::Duration;
+use uuid::Uuid;
+use crate::{
+    middleware::error_handling::{AppError, Result},
+    models::billing::*,
+    services::AiQuotaService,
+};
+
+/// API calls per account since the last flush
+static PENDING_API_CALLS: Lazy<DashMap<Uuid, i64>> = Lazy::new(DashMap::new);
+
+pub struct UsageMeteringService {
+    db_pool: PgPool,
+}
+
+impl UsageMeteringService {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self { db_pool }
+    }
+
+    // ========================================================================
+    // METERING
+    // ========================================================================
+
+    /// Add `quantity` to a counter meter for today
+    pub async fn record(&self, user_id: Uuid, meter: BillingMeter, quantity: i64) {
+        if let Err(e) = self.add_usage(&[user_id], &[quantity], meter).await {
+            tracing::warn!("Failed to meter {} {} for user {}: {}", quantity, meter.as_str(), user_id, e);
+        }
+    }
+
+    /// Count one authenticated API request; written by `flush_api_calls`
+    pub fn count_api_call(user_id: Uuid) {
+        *PENDING_API_CALLS.entry(user_id).or_insert(0) += 1;
+    }
+
+    /// Write the API calls counted since the last flush
+    pub async fn flush_api_calls(&self) -> Result<usize> {
+        let user_ids: Vec<Uuid> = PENDING_API_CALLS.iter().map(|entry| *entry.key()).collect();
+        let (user_ids, counts): (Vec<Uuid>, Vec<i64>) = user_ids
+            .into_iter()
+            .filter_map(|user_id| PENDING_API_CALLS.remove(&user_id))
+            .unzip();
+        if user_ids.is_empty() {
+            return Ok(0);
+        }
+
+        if let Err(e) = self.add_usage(&user_ids, &counts, BillingMeter::ApiCalls).await {
+            // Keep the counts for the next flush
+            for (user_id, count) in user_ids.into_iter().zip(counts) {
+                *PENDING_API_CALLS.entry(user_id).or_insert(0) += count;
+            }
+            return Err(e);
+        }
+        Ok(user_ids.len())
+    }
+
+    async fn add_usage(&self, user_ids: &[Uuid], quantities: &[i64], meter: BillingMeter) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO usage_daily (user_id, meter, usage_date, quantity)
+            SELECT u.user_id, $3, $4, u.quantity
+            FROM UNNEST($1::UUID[], $2::BIGINT[]) AS u(user_id, quantity)
+            JOIN users ON users.id = u.user_id
+            ON CONFLICT (user_id, meter, usage_date) DO UPDATE
+            SET quantity = usage_daily.quantity + EXCLUDED.quantity, updated_at = NOW()
+            "#,
+        )
+        .bind(user_ids)
+        .bind(quantities)
+        .bind(meter.as_str())
+        .bind(Utc::now().date_naive())
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Sample the gauge meters into today's peak
+    pub async fn snapshot_gauges(&self) -> Result<u64> {
+        let today = Utc::now().date_naive();
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO usage_daily (user_id, meter, usage_date, quantity)
+            SELECT user_id, 'listings', $1, COUNT(*)
+            FROM inventory
+            WHERE status = 'available' AND quantity > 0 AND taken_down_at IS NULL
+            GROUP BY user_id
+            UNION ALL
+            SELECT user_id, 'erp_connections', $1, COUNT(*)
+            FROM erp_connections
+            WHERE status <> 'disabled'
+            GROUP BY user_id
+            ON CONFLICT (user_id, meter, usage_date) DO UPDATE
+            SET quantity = GREATEST(usage_daily.quantity, EXCLUDED.quantity), updated_at = NOW()
+            "#,
+        )
+        .bind(today)
+        .execute(&self.db_pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    // ========================================================================
+    // PLANS AND ACCOUNTS
+    // ========================================================================
+
+    pub async fn list_plans(&self) -> Result<Vec<BillingPlanDetails>> {
+        let plans = sqlx::query_as::<_, BillingPlan>(
+            "SELECT * FROM billing_plans WHERE is_active ORDER BY monthly_base_fee_usd, plan",
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+        let meters = sqlx::query_as::<_, BillingPlanMeter>("SELECT * FROM billing_plan_meters ORDER BY plan, meter")
+            .fetch_all(&self.db_pool)
+            .await?;
+
+        Ok(plans
+            .into_iter()
+            .map(|plan| BillingPlanDetails {
+                meters: meters.iter().filter(|m| m.plan == plan.plan).cloned().collect(),
+                plan,
+            })
+            .collect())
+    }
+
+    async fn get_plan(&self, plan: &str) -> Result<BillingPlanDetails> {
+        let plan = sqlx::query_as::<_, BillingPlan>("SELECT * FROM billing_plans WHERE plan = $1")
+            .bind(plan)
+            .fetch_optional(&self.db_pool)
+            .await?
+            .ok_or_else(|| AppError::BadRequest(format!("Unknown billing plan '{}'", plan)))?;
+        let meters = sqlx::query_as::<_, BillingPlanMeter>("SELECT * FROM billing_plan_meters WHERE plan = $1")
+            .bind(&plan.plan)
+            .fetch_all(&self.db_pool)
+            .await?;
+
+        Ok(BillingPlanDetails { plan, meters })
+    }
+
+    pub async fn account(&self, user_id: Uuid) -> Result<BillingAccount> {
+        sqlx::query_as::<_, BillingAccount>(
+            r#"
+            SELECT u.id AS user_id, COALESCE(a.plan, 'free') AS plan, a.notes, a.assigned_by, a.assigned_at
+            FROM users u
+            LEFT JOIN billing_accounts a ON a.user_id = u.id
+            WHERE u.id = $1
+            "#,
+        )
+        .bind(user_id)
+        .fetch_optional(&self.db_pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))
+    }
+
+    /// Move an account to another plan, and to the AI quota tier of the same name if
+    /// there is one
+    pub async fn assign_plan(&self, user_id: Uuid, request: &AssignBillingPlanRequest, assigned_by: Uuid) -> Result<BillingAccount> {
+        let plan = self.get_plan(&request.plan).await?;
+        if !plan.plan.is_active {
+            return Err(AppError::BadRequest(format!("Billing plan '{}' is no longer offered", plan.plan.plan)));
+        }
+        self.account(user_id).await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO billing_accounts (user_id, plan, notes, assigned_by, assigned_at)
+            VALUES ($1, $2, $3, $4, NOW())
+            ON CONFLICT (user_id) DO UPDATE
+            SET plan = EXCLUDED.plan, notes = EXCLUDED.notes,
+                assigned_by = EXCLUDED.assigned_by, assigned_at = EXCLUDED.assigned_at
+            "#,
+        )
+        .bind(user_id)
+        .bind(&plan.plan.plan)
+        .bind(&request.notes)
+        .bind(assigned_by)
+        .execute(&self.db_pool)
+        .await?;
+
+        let has_ai_tier: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM ai_quota_tiers WHERE tier = $1)")
+            .bind(&plan.plan.plan)
+            .fetch_one(&self.db_pool)
+            .await?;
+        if has_ai_tier {
+            AiQuotaService::new(self.db_pool.clone()).set_tier(user_id, &plan.plan.plan).await?;
+        }
+
+        self.account(user_id).await
+    }
+
+    // ========================================================================
+    // USAGE AND STATEMENTS
+    // ========================================================================
+
+    /// Usage of the billing period starting at `period_start`, priced on the current plan
+    pub async fn usage(&self, user_id: Uuid, period_start: NaiveDate) -> Result<UsageReport> {
+        let account = self.account(user_id).await?;
+        let plan = self.get_plan(&account.plan).await?;
+        let period = billing_period(period_start);
+
+        let rows = sqlx::query_as::<_, (String, i64, i64)>(
+            r#"
+            SELECT meter, COALESCE(SUM(quantity), 0)::BIGINT, COALESCE(MAX(quantity), 0)::BIGINT
+            FROM usage_daily
+            WHERE user_id = $1 AND usage_date >= $2 AND usage_date < $3
+            GROUP BY meter
+            "#,
+        )
+        .bind(user_id)
+        .bind(period.0)
+        .bind(period.1)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let quantities: Vec<(BillingMeter, i64)> = rows
+            .into_iter()
+            .filter_map(|(meter, sum, peak)| {
+                let meter = BillingMeter::parse(&meter)?;
+                Some((meter, if meter.is_gauge() { peak } else { sum }))
+            })
+            .collect();
+
+        Ok(UsageReport::new(user_id, &plan, period, &quantities))
+    }
+
+    /// Generate statements for a month that has ended, for every account that had usage
+    /// or is on a paid plan. Accounts that already have one are skipped unless
+    /// `regenerate` is set.
+    pub async fn generate_statements(&self, period_start: NaiveDate, regenerate: bool) -> Result<usize> {
+        let (period_start, period_end) = billing_period(period_start);
+        if period_end > Utc::now().date_naive() {
+            return Err(AppError::BadRequest("Statements can only be generated for a month that has ended".to_string()));
+        }
+
+        let user_ids: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            SELECT user_id FROM usage_daily WHERE usage_date >= $1 AND usage_date < $2
+            UNION
+            SELECT a.user_id FROM billing_accounts a
+            JOIN billing_plans p ON p.plan = a.plan
+            WHERE p.monthly_base_fee_usd > 0
+            EXCEPT
+            SELECT user_id FROM billing_statements WHERE period_start = $1 AND NOT $3
+            "#,
+        )
+        .bind(period_start)
+        .bind(period_end)
+        .bind(regenerate)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let mut generated = 0;
+        for user_id in user_ids {
+            let report = self.usage(user_id, period_start).await?;
+            let inserted = sqlx::query(
+                r#"
+                INSERT INTO billing_statements (
+                    user_id, period_start, period_end, plan, base_fee_usd, usage_fee_usd, total_usd, line_items
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                ON CONFLICT (user_id, period_start) DO UPDATE
+                SET plan = EXCLUDED.plan,
+                    base_fee_usd = EXCLUDED.base_fee_usd,
+                    usage_fee_usd = EXCLUDED.usage_fee_usd,
+                    total_usd = EXCLUDED.total_usd,
+                    line_items = EXCLUDED.line_items,
+                    generated_at = NOW()
+                WHERE $9
+                "#,
+            )
+            .bind(user_id)
+            .bind(report.period_start)
+            .bind(report.period_end)
+            .bind(&report.plan)
+            .bind(report.base_fee_usd)
+            .bind(report.usage_fee_usd)
+            .bind(report.total_usd)
+            .bind(sqlx::types::Json(&report.meters))
+            .bind(regenerate)
+            .execute(&self.db_pool)
+            .await?;
+            generated += inserted.rows_affected() as usize;
+        }
+
+        Ok(generated)
+    }
+
+    pub async fn list_statements(&self, user_id: Uuid) -> Result<Vec<BillingStatement>> {
+        let statements = sqlx::query_as::<_, BillingStatement>(
+            "SELECT * FROM billing_statements WHERE user_id = $1 ORDER BY period_start DESC",
+        )
+        .bind(user_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(statements)
+    }
+
+    pub async fn get_statement(&self, user_id: Uuid, statement_id: Uuid) -> Result<BillingStatement> {
+        sqlx::query_as::<_, BillingStatement>("SELECT * FROM billing_statements WHERE id = $1 AND user_id = $2")
+            .bind(statement_id)
+            .bind(user_id)
+            .fetch_optional(&self.db_pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Billing statement not found".to_string()))
+    }
+}
+
+/// Flushes API call counts every minute; hourly samples the gauges and generates the
+/// previous month's statements (a no-op once they exist)
+pub struct UsageMeteringScheduler {
+    pool: PgPool,
+}
+
+impl UsageMeteringScheduler {
+    /// Flushes per hourly run
+    const FLUSHES_PER_RUN: u32 = 60;
+
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn run(&self) {
+        let mut ticker = tokio::time::interval(Duration::from_secs(60));
+        let service = UsageMeteringService::new(self.pool.clone());
+        let mut ticks = 0;
+
+        loop {
+            ticker.tick().await;
+            // API calls keep accumulating in memory and are flushed once maintenance ends
+            if crate::services::MaintenanceService::pauses("usage_metering") {
+                continue;
+            }
+
+            if let Err(e) = service.flush_api_calls().await {
+                tracing::error!("Usage metering: API call flush failed: {:?}", e);
+            }
+
+            // Each instance flushes its own API calls; the hourly run happens once
+            if ticks % Self::FLUSHES_PER_RUN == 0
+                && crate::services::SchedulerLeader::leads("usage_metering").await
+            {
+                if let Err(e) = service.snapshot_gauges().await {
+                    tracing::error!("Usage metering: gauge snapshot failed: {:?}", e);
+                }
+
+                // An hour into the month, so the last day's API call flushes have landed
+                let (current_month, _) = billing_period((Utc::now() - ChronoDuration::hours(1)).date_naive());
+                let previous_month = current_month - Months::new(1);
+                match service.generate_statements(previous_month, false).await {
+                    Ok(0) => {}
+                    Ok(count) => tracing::info!("Generated {} billing statements for {}", count, previous_month.format("%Y-%m")),
+                    Err(e) => tracing::error!("Billing statement generation failed: {:?}", e),
+                }
+            }
+            ticks = ticks.wrapping_add(1);
+        }
+    }
+}
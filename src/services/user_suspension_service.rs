@@ -0,0 +1,389 @@
+/// User Suspension Service
+///
+/// Admin moderation short of deleting an account:
+/// - suspending a company revokes its sessions, refuses sign-in and token refresh and
+///   hides its listings from the marketplace; timed suspensions are lifted by the
+///   scheduler once they end
+/// - listings can be taken down in bulk (by id or every listing of a company) and
+///   restored, without touching the owner's account
+///
+/// Session revocations are persisted in `users.sessions_revoked_at`; a trigger announces
+/// each one on `SESSION_REVOCATION_CHANNEL` and every instance's listener applies it to
+/// its token blacklist. The scheduler's periodic sync catches anything missed while the
+/// listener was disconnected.
+
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use sqlx::{postgres::PgListener, PgPool};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use uuid::Uuid;
+use crate::{
+    middleware::error_handling::{AppError, Result},
+    models::{alerts::AlertPayload, user_suspension::*},
+    services::{NotificationService, TokenBlacklistService},
+};
+
+pub struct UserSuspensionService {
+    db_pool: PgPool,
+}
+
+impl UserSuspensionService {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self { db_pool }
+    }
+
+    // ========================================================================
+    // SUSPENSIONS
+    // ========================================================================
+
+    /// Suspend the user until `ends_at` (None: indefinitely). The returned suspension's
+    /// `suspended_at` is also the user's session revocation cutoff.
+    pub async fn suspend(
+        &self,
+        user_id: Uuid,
+        reason: &str,
+        ends_at: Option<DateTime<Utc>>,
+        admin_id: Uuid,
+    ) -> Result<UserSuspension> {
+        let mut tx = self.db_pool.begin().await?;
+
+        let suspended: Option<DateTime<Utc>> = sqlx::query_scalar(
+            r#"
+            UPDATE users
+            SET suspended_at = NOW(), suspended_until = $2, suspension_reason = $3,
+                sessions_revoked_at = NOW(), updated_at = NOW()
+            WHERE id = $1 AND suspended_at IS NULL
+            RETURNING suspended_at
+            "#
+        )
+        .bind(user_id)
+        .bind(ends_at)
+        .bind(reason)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if suspended.is_none() {
+            return match self.active_suspension(user_id).await? {
+                Some(_) => Err(AppError::BadRequest("User is already suspended".to_string())),
+                None => Err(AppError::NotFound("User not found".to_string())),
+            };
+        }
+
+        let suspension = sqlx::query_as::<_, UserSuspension>(
+            r#"
+            INSERT INTO user_suspensions (user_id, reason, suspended_by, suspended_at, ends_at)
+            VALUES ($1, $2, $3, NOW(), $4)
+            RETURNING *
+            "#
+        )
+        .bind(user_id)
+        .bind(reason)
+        .bind(admin_id)
+        .bind(ends_at)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        tracing::warn!(
+            "User {} suspended by {} until {}",
+            user_id,
+            admin_id,
+            ends_at.map(|t| t.to_rfc3339()).unwrap_or_else(|| "further notice".to_string())
+        );
+        self.notify(AlertPayload::new_account_suspended(user_id, reason, ends_at)).await;
+
+        Ok(suspension)
+    }
+
+    /// Lift the user's suspension; `admin_id` None when it ended on schedule
+    pub async fn reinstate(&self, user_id: Uuid, admin_id: Option<Uuid>, notes: Option<&str>) -> Result<UserSuspension> {
+        let mut tx = self.db_pool.begin().await?;
+
+        let lifted = sqlx::query(
+            r#"
+            UPDATE users
+            SET suspended_at = NULL, suspended_until = NULL, suspension_reason = NULL, updated_at = NOW()
+            WHERE id = $1 AND suspended_at IS NOT NULL
+            "#
+        )
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        if lifted.rows_affected() == 0 {
+            return Err(AppError::NotFound("User is not suspended".to_string()));
+        }
+
+        let suspension = sqlx::query_as::<_, UserSuspension>(
+            r#"
+            UPDATE user_suspensions
+            SET lifted_at = NOW(), lifted_by = $2, lift_notes = $3
+            WHERE user_id = $1 AND lifted_at IS NULL
+            RETURNING *
+            "#
+        )
+        .bind(user_id)
+        .bind(admin_id)
+        .bind(notes)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        match admin_id {
+            Some(admin_id) => tracing::info!("User {} reinstated by {}", user_id, admin_id),
+            None => tracing::info!("User {} reinstated: suspension ended", user_id),
+        }
+        self.notify(AlertPayload::new_account_reinstated(user_id)).await;
+
+        Ok(suspension)
+    }
+
+    /// The user's open suspension, if any
+    pub async fn active_suspension(&self, user_id: Uuid) -> Result<Option<UserSuspension>> {
+        let suspension = sqlx::query_as::<_, UserSuspension>(
+            "SELECT * FROM user_suspensions WHERE user_id = $1 AND lifted_at IS NULL"
+        )
+        .bind(user_id)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        Ok(suspension)
+    }
+
+    /// Refuse sign-in and token refresh of a suspended user
+    pub async fn ensure_not_suspended(&self, user_id: Uuid) -> Result<()> {
+        let suspension: Option<(Option<String>, Option<DateTime<Utc>>)> = sqlx::query_as(
+            "SELECT suspension_reason, suspended_until FROM users WHERE id = $1 AND suspended_at IS NOT NULL"
+        )
+        .bind(user_id)
+        .fetch_optional(&self.db_pool)
+        .await?;
+
+        match suspension {
+            Some((reason, until)) => Err(AppError::Forbidden(format!(
+                "Account suspended {}: {}",
+                until
+                    .map(|until| format!("until {}", until.format("%Y-%m-%d %H:%M UTC")))
+                    .unwrap_or_else(|| "until further notice".to_string()),
+                reason.unwrap_or_else(|| "contact support".to_string())
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Suspensions of the user, newest first
+    pub async fn history(&self, user_id: Uuid) -> Result<Vec<UserSuspension>> {
+        let suspensions = sqlx::query_as::<_, UserSuspension>(
+            "SELECT * FROM user_suspensions WHERE user_id = $1 ORDER BY suspended_at DESC"
+        )
+        .bind(user_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(suspensions)
+    }
+
+    /// Reinstate users whose timed suspension has ended
+    pub async fn reinstate_expired(&self) -> Result<usize> {
+        let user_ids: Vec<Uuid> = sqlx::query_scalar(
+            "SELECT id FROM users WHERE suspended_at IS NOT NULL AND suspended_until <= NOW()"
+        )
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let mut reinstated = 0;
+        for user_id in user_ids {
+            match self.reinstate(user_id, None, Some("Suspension ended")).await {
+                Ok(_) => reinstated += 1,
+                // Lifted by an admin in the meantime
+                Err(AppError::NotFound(_)) => {}
+                Err(e) => tracing::error!("Failed to reinstate user {}: {:?}", user_id, e),
+            }
+        }
+
+        Ok(reinstated)
+    }
+
+    /// Session revocations that may still match a valid token
+    pub async fn recent_session_revocations(&self) -> Result<Vec<(Uuid, DateTime<Utc>)>> {
+        let revocations = sqlx::query_as::<_, (Uuid, DateTime<Utc>)>(
+            "SELECT id, sessions_revoked_at FROM users WHERE sessions_revoked_at > $1"
+        )
+        .bind(Utc::now() - ChronoDuration::hours(24))
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(revocations)
+    }
+
+    // ========================================================================
+    // LISTING TAKEDOWNS
+    // ========================================================================
+
+    /// Take down the listed inventory ids and/or every listing of `request.user_id`,
+    /// notifying each owner once
+    pub async fn take_down_listings(
+        &self,
+        request: &ListingTakedownRequest,
+        reason: &str,
+        admin_id: Uuid,
+    ) -> Result<ListingTakedownResult> {
+        let taken_down = sqlx::query_as::<_, (Uuid, Uuid)>(
+            r#"
+            UPDATE inventory
+            SET taken_down_at = NOW(), takedown_reason = $3, taken_down_by = $4, updated_at = NOW()
+            WHERE (id = ANY($1) OR user_id = $2) AND taken_down_at IS NULL
+            RETURNING id, user_id
+            "#
+        )
+        .bind(&request.inventory_ids)
+        .bind(request.user_id)
+        .bind(reason)
+        .bind(admin_id)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let mut by_owner: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+        for (inventory_id, owner_id) in &taken_down {
+            by_owner.entry(*owner_id).or_default().push(*inventory_id);
+        }
+        for (owner_id, inventory_ids) in &by_owner {
+            self.notify(AlertPayload::new_listings_taken_down(*owner_id, inventory_ids, reason)).await;
+        }
+
+        tracing::warn!("{} listings of {} companies taken down by {}", taken_down.len(), by_owner.len(), admin_id);
+
+        Ok(ListingTakedownResult {
+            taken_down: taken_down.into_iter().map(|(inventory_id, _)| inventory_id).collect(),
+            owners_notified: by_owner.len(),
+        })
+    }
+
+    /// Put taken-down listings back on the marketplace; returns the restored ids
+    pub async fn restore_listings(&self, inventory_ids: &[Uuid]) -> Result<Vec<Uuid>> {
+        let restored: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            UPDATE inventory
+            SET taken_down_at = NULL, takedown_reason = NULL, taken_down_by = NULL, updated_at = NOW()
+            WHERE id = ANY($1) AND taken_down_at IS NOT NULL
+            RETURNING id
+            "#
+        )
+        .bind(inventory_ids)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        Ok(restored)
+    }
+
+    async fn notify(&self, payload: AlertPayload) {
+        if let Err(e) = NotificationService::new(self.db_pool.clone()).create_alert(payload).await {
+            tracing::error!("Failed to send suspension notification: {:?}", e);
+        }
+    }
+}
+
+// ============================================================================
+// Scheduler
+// ============================================================================
+
+/// Lifts timed suspensions once they end and syncs session revocations made by other
+/// instances into this one's token blacklist
+#[derive(Clone)]
+pub struct UserSuspensionScheduler {
+    pool: PgPool,
+    blacklist: Arc<TokenBlacklistService>,
+}
+
+impl UserSuspensionScheduler {
+    pub fn new(pool: PgPool, blacklist: Arc<TokenBlacklistService>) -> Self {
+        Self { pool, blacklist }
+    }
+
+    pub async fn run(&self) {
+        let mut ticker = tokio::time::interval(Duration::from_secs(60));
+        let service = UserSuspensionService::new(self.pool.clone());
+
+        loop {
+            ticker.tick().await;
+
+            match service.recent_session_revocations().await {
+                Ok(revocations) => {
+                    for (user_id, revoked_at) in revocations {
+                        self.blacklist.revoke_sessions_before(user_id, revoked_at.timestamp(), "sync");
+                    }
+                }
+                Err(e) => tracing::error!("Session revocation sync failed: {:?}", e),
+            }
+
+            // Revocation sync only reads, so it keeps running during maintenance
+            if crate::services::MaintenanceService::pauses("suspension_expiry")
+                || !crate::services::SchedulerLeader::leads("suspension_expiry").await
+            {
+                continue;
+            }
+            match service.reinstate_expired().await {
+                Ok(0) => {}
+                Ok(count) => tracing::info!("Reinstated {} users whose suspension ended", count),
+                Err(e) => tracing::error!("Suspension expiry check failed: {:?}", e),
+            }
+        }
+    }
+
+    /// LISTEN for session revocations from any instance and apply them to the local
+    /// blacklist. Runs for the life of the process; after a lost connection recent
+    /// revocations are re-synced, since notifications may have been missed.
+    pub async fn run_listener(self) {
+        let service = UserSuspensionService::new(self.pool.clone());
+
+        loop {
+            let mut listener = match PgListener::connect_with(&self.pool).await {
+                Ok(listener) => listener,
+                Err(e) => {
+                    tracing::error!("❌ Session revocation listener connection failed: {}", e);
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    continue;
+                }
+            };
+            if let Err(e) = listener.listen(SESSION_REVOCATION_CHANNEL).await {
+                tracing::error!("❌ Session revocation LISTEN failed: {}", e);
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                continue;
+            }
+
+            // Catch up on anything revoked while not listening
+            match service.recent_session_revocations().await {
+                Ok(revocations) => {
+                    for (user_id, revoked_at) in revocations {
+                        self.blacklist.revoke_sessions_before(user_id, revoked_at.timestamp(), "sync");
+                    }
+                }
+                Err(e) => tracing::error!("Session revocation sync failed: {:?}", e),
+            }
+
+            loop {
+                match listener.try_recv().await {
+                    Ok(Some(notification)) => {
+                        match serde_json::from_str::<SessionRevocationNotice>(notification.payload()) {
+                            Ok(notice) => {
+                                self.blacklist.revoke_sessions_before(notice.user_id, notice.revoked_at, "broadcast");
+                            }
+                            Err(e) => tracing::warn!("Ignoring malformed session revocation notice: {}", e),
+                        }
+                    }
+                    // Connection lost; reconnect and re-sync
+                    Ok(None) => {
+                        tracing::warn!("Session revocation listener connection lost, reconnecting");
+                        break;
+                    }
+                    Err(e) => {
+                        tracing::warn!("Session revocation listener failed, reconnecting: {}", e);
+                        break;
+                    }
+                }
+            }
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+    }
+}
@@ -0,0 +1,236 @@
+/// Verification Service
+///
+/// Decisions on the verification queue (unverified companies that have not been
+/// rejected):
+/// - the queue shows each applicant's uploaded licenses so documents are reviewed
+///   with the application
+/// - admins approve or reject applicants in bulk; every decision is recorded in
+///   `verification_decisions`, audited and sent to the applicant as a notification
+/// - the daily `verification_auto_reject` job rejects applications without activity
+///   (sign-up or license upload) for `verification.auto_reject_days`
+///
+/// Rejected applicants leave the queue until they upload a license again (see the
+/// `reopen_rejected_verification` trigger).
+
+use sqlx::PgPool;
+use std::collections::HashMap;
+use uuid::Uuid;
+use crate::{
+    middleware::error_handling::{AppError, Result},
+    models::{alerts::AlertPayload, license::BusinessLicense, verification::*},
+    services::{
+        admin_service::VerificationQueueItem,
+        comprehensive_audit_service::{ActionResult, AuditLogEntry, ComprehensiveAuditService, EventCategory, Severity},
+        NotificationService,
+    },
+};
+
+pub struct VerificationService {
+    db_pool: PgPool,
+}
+
+impl VerificationService {
+    pub fn new(db_pool: PgPool) -> Self {
+        Self { db_pool }
+    }
+
+    /// Attach each applicant's licenses, newest first
+    pub async fn attach_licenses(&self, queue: &mut [VerificationQueueItem]) -> Result<()> {
+        let user_ids: Vec<Uuid> = queue.iter().map(|item| item.user.id).collect();
+
+        let licenses = sqlx::query_as::<_, BusinessLicense>(
+            "SELECT * FROM business_licenses WHERE user_id = ANY($1) ORDER BY created_at DESC"
+        )
+        .bind(&user_ids)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let mut by_user: HashMap<Uuid, Vec<QueueLicense>> = HashMap::new();
+        for license in licenses {
+            by_user.entry(license.user_id).or_default().push(license.into());
+        }
+        for item in queue.iter_mut() {
+            item.licenses = by_user.remove(&item.user.id).unwrap_or_default();
+        }
+
+        Ok(())
+    }
+
+    /// Apply each decision on its own; one failing or stale decision doesn't stop the rest
+    pub async fn decide_bulk(&self, request: &BulkVerificationRequest, admin_id: Uuid) -> Result<BulkVerificationResult> {
+        let decisions = request
+            .validate()
+            .map_err(AppError::BadRequest)?;
+
+        let mut result = BulkVerificationResult::default();
+        for (user_id, kind, reason) in decisions {
+            match self.decide(user_id, kind, reason.as_deref(), Some(admin_id)).await {
+                Ok(true) => {
+                    let status = match kind {
+                        VerificationDecisionKind::Approve => VerificationOutcomeStatus::Approved,
+                        VerificationDecisionKind::Reject => VerificationOutcomeStatus::Rejected,
+                    };
+                    result.push(user_id, status, None);
+                }
+                Ok(false) => result.push(
+                    user_id,
+                    VerificationOutcomeStatus::Skipped,
+                    Some("Not a pending applicant".to_string()),
+                ),
+                Err(e) => {
+                    tracing::error!("Verification decision for user {} failed: {:?}", user_id, e);
+                    result.push(user_id, VerificationOutcomeStatus::Failed, Some("Decision could not be saved".to_string()));
+                }
+            }
+        }
+
+        tracing::info!(
+            "Verification decisions by admin {}: {} approved, {} rejected, {} skipped, {} failed",
+            admin_id,
+            result.approved,
+            result.rejected,
+            result.skipped,
+            result.failed
+        );
+        Ok(result)
+    }
+
+    /// Reject pending applications without activity for `days`. Returns the number rejected.
+    pub async fn auto_reject_stale(&self, days: i64) -> Result<usize> {
+        let user_ids: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            SELECT u.id
+            FROM users u
+            WHERE u.role = 'user'
+              AND u.is_verified = false
+              AND u.verification_rejected_at IS NULL
+              AND GREATEST(
+                    u.created_at,
+                    (SELECT MAX(l.created_at) FROM business_licenses l WHERE l.user_id = u.id)
+                  ) < NOW() - make_interval(days => $1)
+            "#
+        )
+        .bind(days as i32)
+        .fetch_all(&self.db_pool)
+        .await?;
+
+        let reason = format!("the application had no activity for {} days", days);
+        let mut rejected = 0;
+        for user_id in user_ids {
+            match self.decide(user_id, VerificationDecisionKind::Reject, Some(&reason), None).await {
+                Ok(true) => rejected += 1,
+                // Decided by an admin in the meantime
+                Ok(false) => {}
+                Err(e) => tracing::error!("Failed to auto-reject verification of user {}: {:?}", user_id, e),
+            }
+        }
+
+        Ok(rejected)
+    }
+
+    /// Approve or reject one pending applicant; false when the user is not pending.
+    /// `decided_by` is None for automatic rejections.
+    async fn decide(
+        &self,
+        user_id: Uuid,
+        kind: VerificationDecisionKind,
+        reason: Option<&str>,
+        decided_by: Option<Uuid>,
+    ) -> Result<bool> {
+        let mut tx = self.db_pool.begin().await?;
+
+        let update = match kind {
+            VerificationDecisionKind::Approve => {
+                r#"
+                UPDATE users
+                SET is_verified = true, updated_at = NOW()
+                WHERE id = $1 AND role = 'user' AND is_verified = false AND verification_rejected_at IS NULL
+                RETURNING id
+                "#
+            }
+            VerificationDecisionKind::Reject => {
+                r#"
+                UPDATE users
+                SET verification_rejected_at = NOW(), verification_rejection_reason = $2, updated_at = NOW()
+                WHERE id = $1 AND role = 'user' AND is_verified = false AND verification_rejected_at IS NULL
+                RETURNING id
+                "#
+            }
+        };
+        let mut query = sqlx::query_scalar::<_, Uuid>(update).bind(user_id);
+        if kind == VerificationDecisionKind::Reject {
+            query = query.bind(reason);
+        }
+        if query.fetch_optional(&mut *tx).await?.is_none() {
+            return Ok(false);
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO verification_decisions (user_id, decision, reason, decided_by, automatic)
+            VALUES ($1, $2, $3, $4, $5)
+            "#
+        )
+        .bind(user_id)
+        .bind(kind.as_str())
+        .bind(reason)
+        .bind(decided_by)
+        .bind(decided_by.is_none())
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        self.audit(user_id, kind, reason, decided_by).await?;
+
+        let rejection_reason = match kind {
+            VerificationDecisionKind::Approve => None,
+            VerificationDecisionKind::Reject => reason,
+        };
+        if let Err(e) = NotificationService::new(self.db_pool.clone())
+            .create_alert(AlertPayload::new_verification_decided(user_id, rejection_reason))
+            .await
+        {
+            tracing::warn!("Could not notify user {} about their verification: {:?}", user_id, e);
+        }
+
+        Ok(true)
+    }
+
+    async fn audit(
+        &self,
+        user_id: Uuid,
+        kind: VerificationDecisionKind,
+        reason: Option<&str>,
+        decided_by: Option<Uuid>,
+    ) -> Result<()> {
+        // Approvals share the event of single verifications, so verification history
+        // (and the signup funnel) reads one event type
+        let (event_type, action) = match kind {
+            VerificationDecisionKind::Approve => ("admin_verify_user", "verify_user"),
+            VerificationDecisionKind::Reject => ("admin_reject_verification", "reject_verification"),
+        };
+
+        ComprehensiveAuditService::new(self.db_pool.clone()).log(AuditLogEntry {
+            event_type: event_type.to_string(),
+            event_category: EventCategory::Admin,
+            severity: Severity::Warning,
+            actor_user_id: decided_by,
+            actor_type: if decided_by.is_some() { "user" } else { "system" }.to_string(),
+            resource_type: Some("user".to_string()),
+            resource_id: Some(user_id.to_string()),
+            action: action.to_string(),
+            action_result: ActionResult::Success,
+            event_data: serde_json::json!({
+                "user_id": user_id,
+                "new_status": kind == VerificationDecisionKind::Approve,
+                "reason": reason,
+                "automatic": decided_by.is_none(),
+            }),
+            compliance_tags: vec!["admin".to_string(), "verification".to_string(), "compliance".to_string()],
+            ..Default::default()
+        }).await?;
+
+        Ok(())
+    }
+}
@@ -8,6 +8,10 @@ use uuid::Uuid;
 
 type HmacSha256 = Hmac<Sha256>;
 
+/// Webhook requests allowed per connection and window (`v_max_requests` of
+/// `check_webhook_rate_limit`, migration 013)
+pub const WEBHOOK_RATE_LIMIT_MAX_REQUESTS: i32 = 100;
+
 /// Webhook security service for signature verification and rate limiting
 pub struct WebhookSecurityService {
     pool: PgPool,
@@ -20,9 +24,21 @@ pub struct WebhookVerificationResult {
     pub signature_valid: bool,
     pub rate_limit_allowed: bool,
     pub requests_remaining: i32,
+    pub reset_at: DateTime<Utc>,
     pub blocked: bool,
 }
 
+impl WebhookVerificationResult {
+    /// X-RateLimit-* headers of the connection's budget
+    pub fn rate_limit_headers(&self) -> [(&'static str, String); 3] {
+        [
+            ("X-RateLimit-Limit", WEBHOOK_RATE_LIMIT_MAX_REQUESTS.to_string()),
+            ("X-RateLimit-Remaining", self.requests_remaining.max(0).to_string()),
+            ("X-RateLimit-Reset", self.reset_at.timestamp().to_string()),
+        ]
+    }
+}
+
 #[derive(Debug)]
 pub struct WebhookAuditLog {
     pub connection_id: Uuid,
@@ -116,6 +132,7 @@ impl WebhookSecurityService {
             signature_valid: false, // Will be set by caller after signature verification
             rate_limit_allowed: result.allowed,
             requests_remaining: result.requests_remaining,
+            reset_at: result.reset_at,
             blocked: result.blocked,
         })
     }
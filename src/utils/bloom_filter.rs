@@ -0,0 +1,127 @@
+// ============================================================================
+// Bloom Filter - Compact, serializable set membership
+// ============================================================================
+//
+// Used by the OpenFDA sync to persist the set of NDCs seen so far in its resume
+// checkpoint. A few hundred KB replaces a HashSet of ~150k strings, at the cost
+// of false positives: `contains` may return true for an item that was never
+// inserted, so callers must confirm positives before acting on them.
+//
+// Hashing uses SHA-256 (double hashing on two 64-bit halves) rather than the std
+// hasher so serialized filters stay valid across builds and restarts.
+//
+// ============================================================================
+
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest, Sha256};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BloomFilter {
+    #[serde(serialize_with = "serialize_bits", deserialize_with = "deserialize_bits")]
+    bits: Vec<u8>,
+    num_bits: u64,
+    num_hashes: u32,
+    items: u64,
+}
+
+impl BloomFilter {
+    /// Size a filter for `expected_items` at the given false positive rate (e.g. 0.01)
+    pub fn with_capacity(expected_items: usize, false_positive_rate: f64) -> Self {
+        let n = expected_items.max(1) as f64;
+        let p = false_positive_rate.clamp(1e-9, 0.5);
+        let ln2 = std::f64::consts::LN_2;
+
+        let num_bits = ((-n * p.ln()) / (ln2 * ln2)).ceil().max(8.0) as u64;
+        let num_hashes = ((num_bits as f64 / n) * ln2).round().clamp(1.0, 16.0) as u32;
+
+        Self {
+            bits: vec![0; num_bits.div_ceil(8) as usize],
+            num_bits,
+            num_hashes,
+            items: 0,
+        }
+    }
+
+    pub fn insert(&mut self, item: &str) {
+        for index in self.indexes(item) {
+            self.bits[(index / 8) as usize] |= 1 << (index % 8);
+        }
+        self.items += 1;
+    }
+
+    /// True if the item may have been inserted; false means it definitely was not
+    pub fn contains(&self, item: &str) -> bool {
+        self.indexes(item)
+            .all(|index| self.bits[(index / 8) as usize] & (1 << (index % 8)) != 0)
+    }
+
+    /// Number of insert calls (duplicates included)
+    pub fn len(&self) -> u64 {
+        self.items
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items == 0
+    }
+
+    fn indexes(&self, item: &str) -> impl Iterator<Item = u64> {
+        let digest = Sha256::digest(item.as_bytes());
+        let h1 = u64::from_le_bytes(digest[0..8].try_into().unwrap_or_default());
+        let h2 = u64::from_le_bytes(digest[8..16].try_into().unwrap_or_default()) | 1;
+        let num_bits = self.num_bits;
+
+        (0..self.num_hashes as u64).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % num_bits)
+    }
+}
+
+fn serialize_bits<S: Serializer>(bits: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&BASE64.encode(bits))
+}
+
+fn deserialize_bits<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    let encoded = String::deserialize(deserializer)?;
+    BASE64.decode(encoded).map_err(serde::de::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inserted_items_are_found() {
+        let mut filter = BloomFilter::with_capacity(1_000, 0.01);
+        for i in 0..1_000 {
+            filter.insert(&format!("0002-{:04}", i));
+        }
+        assert!((0..1_000).all(|i| filter.contains(&format!("0002-{:04}", i))));
+        assert_eq!(filter.len(), 1_000);
+    }
+
+    #[test]
+    fn test_false_positive_rate_is_bounded() {
+        let mut filter = BloomFilter::with_capacity(10_000, 0.01);
+        for i in 0..10_000 {
+            filter.insert(&format!("seen-{}", i));
+        }
+        let false_positives = (0..10_000)
+            .filter(|i| filter.contains(&format!("unseen-{}", i)))
+            .count();
+        // 1% target; allow generous slack
+        assert!(false_positives < 300, "false positives: {}", false_positives);
+    }
+
+    #[test]
+    fn test_serialization_round_trip() {
+        let mut filter = BloomFilter::with_capacity(100, 0.01);
+        filter.insert("50090-1234");
+
+        let json = serde_json::to_value(&filter).unwrap();
+        assert!(json["bits"].is_string());
+
+        let restored: BloomFilter = serde_json::from_value(json).unwrap();
+        assert_eq!(restored, filter);
+        assert!(restored.contains("50090-1234"));
+        assert!(!restored.is_empty());
+    }
+}
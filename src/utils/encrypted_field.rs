@@ -0,0 +1,150 @@
+/// Column-level encryption for sqlx models
+///
+/// `EncryptedField<T>` is the AES-256-GCM ciphertext of a `T` as stored in a TEXT
+/// column. It decodes from and binds to the column like a string, but the plaintext
+/// is only reachable through `Decrypt::decrypt`, so a model built from encrypted
+/// columns can't skip decryption or leak ciphertext as plaintext. It implements
+/// neither `Serialize` nor `Display`, and `Debug` doesn't print the ciphertext.
+///
+/// ```ignore
+/// #[derive(FromRow)]
+/// struct UserRow {
+///     phone_encrypted: Option<EncryptedField>,
+/// }
+///
+/// let phone: Option<String> = row.phone_encrypted.decrypt(&encryption)?;
+/// ```
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::str::FromStr;
+use sqlx::encode::IsNull;
+use sqlx::error::BoxDynError;
+use sqlx::postgres::{PgArgumentBuffer, PgTypeInfo, PgValueRef};
+use sqlx::{Decode, Encode, Postgres, Type};
+use crate::services::encryption_service::{EncryptionError, EncryptionService, Result};
+
+pub struct EncryptedField<T = String> {
+    ciphertext: String,
+    plaintext: PhantomData<fn() -> T>,
+}
+
+impl<T> EncryptedField<T> {
+    /// Wrap ciphertext read outside of sqlx decoding
+    pub fn from_ciphertext(ciphertext: String) -> Self {
+        Self { ciphertext, plaintext: PhantomData }
+    }
+
+    pub fn ciphertext(&self) -> &str {
+        &self.ciphertext
+    }
+}
+
+impl<T: ToString> EncryptedField<T> {
+    pub fn encrypt(encryption: &EncryptionService, value: &T) -> Result<Self> {
+        Ok(Self::from_ciphertext(encryption.encrypt(&value.to_string())?))
+    }
+
+    pub fn encrypt_optional(encryption: &EncryptionService, value: Option<&T>) -> Result<Option<Self>> {
+        value.map(|value| Self::encrypt(encryption, value)).transpose()
+    }
+}
+
+impl<T> Clone for EncryptedField<T> {
+    fn clone(&self) -> Self {
+        Self::from_ciphertext(self.ciphertext.clone())
+    }
+}
+
+impl<T> fmt::Debug for EncryptedField<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("EncryptedField(..)")
+    }
+}
+
+/// Turns encrypted columns back into plaintext
+pub trait Decrypt {
+    type Plaintext;
+
+    fn decrypt(&self, encryption: &EncryptionService) -> Result<Self::Plaintext>;
+}
+
+impl<T> Decrypt for EncryptedField<T>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    type Plaintext = T;
+
+    fn decrypt(&self, encryption: &EncryptionService) -> Result<T> {
+        encryption
+            .decrypt(&self.ciphertext)?
+            .parse()
+            .map_err(|e: T::Err| EncryptionError::DecryptionFailed(e.to_string()))
+    }
+}
+
+/// NULL columns stay `None`
+impl<T> Decrypt for Option<EncryptedField<T>>
+where
+    EncryptedField<T>: Decrypt,
+{
+    type Plaintext = Option<<EncryptedField<T> as Decrypt>::Plaintext>;
+
+    fn decrypt(&self, encryption: &EncryptionService) -> Result<Self::Plaintext> {
+        self.as_ref().map(|field| field.decrypt(encryption)).transpose()
+    }
+}
+
+impl<T> Type<Postgres> for EncryptedField<T> {
+    fn type_info() -> PgTypeInfo {
+        <String as Type<Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        <String as Type<Postgres>>::compatible(ty)
+    }
+}
+
+impl<'r, T> Decode<'r, Postgres> for EncryptedField<T> {
+    fn decode(value: PgValueRef<'r>) -> std::result::Result<Self, BoxDynError> {
+        Ok(Self::from_ciphertext(<String as Decode<Postgres>>::decode(value)?))
+    }
+}
+
+impl<'q, T> Encode<'q, Postgres> for EncryptedField<T> {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> IsNull {
+        <String as Encode<Postgres>>::encode_by_ref(&self.ciphertext, buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encryption() -> EncryptionService {
+        EncryptionService::new(&EncryptionService::generate_key()).unwrap()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let encryption = encryption();
+
+        let field = EncryptedField::encrypt(&encryption, &"+1 555 0100".to_string()).unwrap();
+        assert_ne!(field.ciphertext(), "+1 555 0100");
+        assert_eq!(field.decrypt(&encryption).unwrap(), "+1 555 0100");
+        assert_eq!(format!("{:?}", field), "EncryptedField(..)");
+
+        let count = EncryptedField::<u32>::encrypt(&encryption, &42).unwrap();
+        assert_eq!(count.decrypt(&encryption).unwrap(), 42);
+
+        let missing: Option<EncryptedField> = EncryptedField::encrypt_optional(&encryption, None).unwrap();
+        assert_eq!(missing.decrypt(&encryption).unwrap(), None);
+    }
+
+    #[test]
+    fn test_wrong_key_fails() {
+        let field = EncryptedField::encrypt(&encryption(), &"Jane Doe".to_string()).unwrap();
+        assert!(field.decrypt(&encryption()).is_err());
+    }
+}
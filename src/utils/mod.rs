@@ -1,6 +1,13 @@
 pub mod file_storage;
 pub mod encrypted_file_storage;
+pub mod encrypted_field;
 pub mod log_sanitizer;
+pub mod bloom_filter;
+pub mod request_budget;
+pub mod ndc;
 
 pub use encrypted_file_storage::EncryptedFileStorage;
+pub use encrypted_field::{Decrypt, EncryptedField};
 pub use log_sanitizer::*;
+pub use bloom_filter::BloomFilter;
+pub use request_budget::RequestBudget;
@@ -0,0 +1,89 @@
+// ============================================================================
+// NDC - National Drug Code normalization shared by EDI and OpenFDA lookups
+// ============================================================================
+//
+// A 10-digit NDC is split 4-4-2, 5-3-2 or 5-4-1 (labeler-product-package); the
+// 11-digit billing form pads it to 5-4-2. Without hyphens a 10-digit code does
+// not say which layout it uses, so it cannot be converted on its own.
+//
+// ============================================================================
+
+/// 11-digit NDC (5-4-2), from a hyphenated 10-digit NDC or an 11-digit NDC.
+/// Unhyphenated 10-digit codes are ambiguous and yield `None`.
+pub fn ndc_to_11_digit(ndc: &str) -> Option<String> {
+    let parts: Vec<&str> = ndc.trim().split('-').collect();
+    if !parts.iter().all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit())) {
+        return None;
+    }
+
+    match parts.as_slice() {
+        [single] if single.len() == 11 => Some(single.to_string()),
+        [labeler, product, package] => {
+            let padded = format!("{:0>5}{:0>4}{:0>2}", labeler, product, package);
+            (labeler.len() <= 5 && product.len() <= 4 && package.len() <= 2 && padded.len() == 11)
+                .then_some(padded)
+        }
+        _ => None,
+    }
+}
+
+/// Hyphenated forms an NDC may stand for. A hyphenated NDC is returned as is; without
+/// hyphens every 10-digit layout (4-4-2, 5-3-2, 5-4-1) is a candidate, and an 11-digit
+/// (5-4-2) code is unpadded at each segment that starts with 0. More than one candidate
+/// means the layout still has to be resolved, e.g. against the catalog.
+pub fn hyphenated_ndcs(ndc: &str) -> Vec<String> {
+    let ndc = ndc.trim();
+    if ndc.contains('-') {
+        return vec![ndc.to_string()];
+    }
+    if ndc.len() == 10 && ndc.chars().all(|c| c.is_ascii_digit()) {
+        return vec![
+            format!("{}-{}-{}", &ndc[..4], &ndc[4..8], &ndc[8..]),
+            format!("{}-{}-{}", &ndc[..5], &ndc[5..8], &ndc[8..]),
+            format!("{}-{}-{}", &ndc[..5], &ndc[5..9], &ndc[9..]),
+        ];
+    }
+
+    let Some(padded) = ndc_to_11_digit(ndc) else {
+        return Vec::new();
+    };
+    let (labeler, product, package) = (&padded[..5], &padded[5..9], &padded[9..]);
+    let mut candidates = Vec::new();
+    if let Some(labeler) = labeler.strip_prefix('0') {
+        candidates.push(format!("{}-{}-{}", labeler, product, package));
+    }
+    if let Some(product) = product.strip_prefix('0') {
+        candidates.push(format!("{}-{}-{}", labeler, product, package));
+    }
+    if let Some(package) = package.strip_prefix('0') {
+        candidates.push(format!("{}-{}-{}", labeler, product, package));
+    }
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ndc_to_11_digit() {
+        assert_eq!(ndc_to_11_digit("0002-3227-30").as_deref(), Some("00002322730"));
+        assert_eq!(ndc_to_11_digit("50090-347-0").as_deref(), Some("50090034700"));
+        assert_eq!(ndc_to_11_digit("00002322730").as_deref(), Some("00002322730"));
+        assert_eq!(ndc_to_11_digit("0002322730"), None);
+    }
+
+    #[test]
+    fn test_hyphenated_ndcs() {
+        assert_eq!(hyphenated_ndcs("0069-3150-83"), vec!["0069-3150-83"]);
+        assert_eq!(
+            hyphenated_ndcs("0069315083"),
+            vec!["0069-3150-83", "00693-150-83", "00693-1508-3"]
+        );
+        // 11-digit 00069-3150-83 is the 4-4-2 code 0069-3150-83
+        assert_eq!(hyphenated_ndcs("00069315083"), vec!["0069-3150-83"]);
+        assert_eq!(hyphenated_ndcs("50090012301"), vec!["50090-123-01", "50090-0123-1"]);
+        assert!(hyphenated_ndcs("12345").is_empty());
+        assert!(hyphenated_ndcs("abcdefghijk").is_empty());
+    }
+}
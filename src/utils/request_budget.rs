@@ -0,0 +1,95 @@
+// ============================================================================
+// Request Budget - Shared pacing for outbound sync requests
+// ============================================================================
+//
+// Every worker of a sync draws request slots from one budget, so concurrency
+// never exceeds the source's rate limit. Slots are spaced `60s / requests` apart;
+// a burst limit above 1 lets an idle budget bank up to that many slots, so the
+// first requests after a pause go out immediately (a generic cell rate algorithm).
+//
+// ============================================================================
+
+use std::time::{Duration, Instant};
+
+pub struct RequestBudget {
+    interval: Duration,
+    burst: u32,
+    next_slot: std::sync::Mutex<Instant>,
+}
+
+impl RequestBudget {
+    pub fn per_minute(requests: u32) -> Self {
+        Self::with_burst(requests, 1)
+    }
+
+    pub fn with_burst(requests_per_minute: u32, burst: u32) -> Self {
+        Self {
+            interval: Duration::from_secs(60) / requests_per_minute.max(1),
+            burst: burst.max(1),
+            next_slot: std::sync::Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Wait for the next free request slot
+    pub async fn acquire(&self) {
+        let slot = self.reserve(Instant::now());
+        tokio::time::sleep_until(tokio::time::Instant::from_std(slot)).await;
+    }
+
+    /// Claim the earliest slot at or after `now`
+    pub fn reserve(&self, now: Instant) -> Instant {
+        let mut next = self.next_slot.lock().unwrap_or_else(|e| e.into_inner());
+        // An idle budget banks at most `burst - 1` slots
+        let banked = self.interval * (self.burst - 1);
+        let earliest = now.checked_sub(banked).unwrap_or(now);
+        let slot = (*next).max(earliest);
+        *next = slot + self.interval;
+        slot.max(now)
+    }
+
+    /// Hold back every pending request (after a 429)
+    pub fn pause(&self, duration: Duration) {
+        let mut next = self.next_slot.lock().unwrap_or_else(|e| e.into_inner());
+        *next = (*next).max(Instant::now() + duration);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_budget_spaces_slots() {
+        let budget = RequestBudget::per_minute(60);
+        let now = Instant::now();
+
+        assert_eq!(budget.reserve(now), now);
+        assert_eq!(budget.reserve(now), now + Duration::from_secs(1));
+        assert_eq!(budget.reserve(now), now + Duration::from_secs(2));
+
+        // An idle budget does not bank slots
+        let later = now + Duration::from_secs(30);
+        assert_eq!(budget.reserve(later), later);
+    }
+
+    #[test]
+    fn test_request_budget_burst() {
+        let budget = RequestBudget::with_burst(60, 3);
+        let now = Instant::now() + Duration::from_secs(10);
+
+        // Three requests go out at once, then slots are spaced again
+        assert_eq!(budget.reserve(now), now);
+        assert_eq!(budget.reserve(now), now);
+        assert_eq!(budget.reserve(now), now);
+        assert_eq!(budget.reserve(now), now + Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_request_budget_pause_delays_all_requests() {
+        let budget = RequestBudget::per_minute(600);
+        budget.pause(Duration::from_secs(60));
+
+        let slot = budget.reserve(Instant::now());
+        assert!(slot >= Instant::now() + Duration::from_secs(59));
+    }
+}
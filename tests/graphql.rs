@@ -0,0 +1,65 @@
+// GraphQL Schema
+// Checks the schema builds with its root fields and that a request is refused before
+// any resolver touches the database: without claims, above the depth or complexity
+// limit, or with a persisted query hash the server doesn't know
+// Run with: cargo test --test graphql
+
+use atlas_pharma::graphql::{schema, sdl};
+use serde_json::json;
+
+#[test]
+fn schema_exposes_root_fields() {
+    let sdl = sdl();
+    for field in [
+        "me:",
+        "pharmaceuticals(",
+        "catalogSearch(",
+        "myInventory(",
+        "marketplaceSearch(",
+        "inquiries(",
+        "transactions(",
+        "notifications(",
+        "markNotificationRead(",
+        "dismissNotification(",
+    ] {
+        assert!(sdl.contains(field), "SDL has no root field {}", field);
+    }
+}
+
+#[tokio::test]
+async fn unauthenticated_request_is_unauthorized() {
+    let response = schema().execute("{ me { id } }").await;
+    let error = &response.errors[0];
+    let status = error.extensions.as_ref().and_then(|extensions| extensions.get("status")).cloned();
+    assert_eq!(status, Some(async_graphql::Value::from(401u16)));
+}
+
+#[tokio::test]
+async fn deep_query_is_rejected() {
+    let query = "{ transactions { inquiry { listing { pharmaceutical { catalogEntry { id } } } } } __schema { types { fields { type { ofType { ofType { ofType { ofType { ofType { ofType { name } } } } } } } } } } }";
+    let response = schema().execute(query).await;
+    assert!(response.errors.iter().any(|error| error.message.contains("too deep")), "{:?}", response.errors);
+}
+
+#[tokio::test]
+async fn large_page_counts_against_complexity() {
+    let query = "{ pharmaceuticals(limit: 100) { id brandName genericName ndcCode manufacturer category description strength dosageForm udiDi createdAt } }";
+    let response = schema().execute(query).await;
+    assert!(response.errors.iter().any(|error| error.message.contains("too complex")), "{:?}", response.errors);
+}
+
+#[tokio::test]
+async fn unknown_persisted_query_asks_for_the_text() {
+    let request: async_graphql::Request = serde_json::from_value(json!({
+        "query": "",
+        "extensions": {
+            "persistedQuery": {
+                "version": 1,
+                "sha256Hash": "ecf4edb46db40b5132295c0291d62fb65d6759a9eedfa4d5d612dd5ec54a6b38"
+            }
+        }
+    }))
+    .unwrap();
+    let response = schema().execute(request).await;
+    assert_eq!(response.errors[0].message, "PersistedQueryNotFound");
+}
@@ -0,0 +1,65 @@
+// OpenAPI Spec Coverage
+// Fails when a route in src/main.rs has no operation in the OpenAPI spec (its handler
+// lacks #[utoipa::path] or isn't listed in ApiDoc), or the spec documents a route the
+// router doesn't have
+// Run with: cargo test --test openapi
+
+use std::collections::BTreeSet;
+
+use atlas_pharma::middleware::route_policy::RoutePolicyReport;
+use atlas_pharma::openapi::{document_route_policies, documented_routes, openapi_path, ApiDoc};
+use utoipa::OpenApi;
+
+const MAIN_RS: &str = include_str!("../src/main.rs");
+
+#[test]
+fn every_route_is_documented() {
+    let report = RoutePolicyReport::from_source(MAIN_RS);
+    let routes: BTreeSet<(String, String)> = report
+        .routes
+        .iter()
+        .map(|route| (route.method.clone(), openapi_path(&route.path)))
+        .collect();
+    let documented: BTreeSet<(String, String)> = documented_routes(&ApiDoc::openapi()).into_iter().collect();
+
+    let undocumented: Vec<_> = routes.difference(&documented).collect();
+    assert!(
+        undocumented.is_empty(),
+        "Routes without an OpenAPI operation (annotate the handler with #[utoipa::path] and list it in ApiDoc):\n{:#?}",
+        undocumented
+    );
+
+    let stale: Vec<_> = documented.difference(&routes).collect();
+    assert!(stale.is_empty(), "OpenAPI operations without a route:\n{:#?}", stale);
+}
+
+#[test]
+fn operation_ids_are_unique() {
+    let openapi = ApiDoc::openapi();
+    let mut seen = BTreeSet::new();
+    for item in openapi.paths.paths.values() {
+        for operation in [&item.get, &item.put, &item.post, &item.delete, &item.patch].into_iter().flatten() {
+            let id = operation.operation_id.clone().expect("operation without an id");
+            assert!(seen.insert(id.clone()), "Duplicate operationId {} (set operation_id in #[utoipa::path])", id);
+        }
+    }
+}
+
+#[test]
+fn route_policies_set_security() {
+    let report = RoutePolicyReport::from_source(MAIN_RS);
+    let mut openapi = ApiDoc::openapi();
+    document_route_policies(&mut openapi, &report);
+
+    let login = openapi.paths.paths["/api/auth/login"].post.as_ref().unwrap();
+    assert_eq!(login.security.as_ref().map(|security| security.len()), Some(0));
+
+    let profile = openapi.paths.paths["/api/auth/profile"].get.as_ref().unwrap();
+    assert!(profile.security.is_none(), "authenticated routes use the global security requirement");
+
+    let users = openapi.paths.paths["/api/admin/users"].get.as_ref().unwrap();
+    assert!(users.description.as_deref().unwrap_or_default().contains("admin role"));
+
+    let schemes = &openapi.components.as_ref().unwrap().security_schemes;
+    assert!(schemes.contains_key("bearer_auth") && schemes.contains_key("cookie_auth"));
+}
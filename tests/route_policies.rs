@@ -0,0 +1,45 @@
+// Route Policy Audit
+// Fails when a route in src/main.rs lacks the protection it needs: auth for every
+// route, admin for /api/admin, or an explicit entry in PUBLIC_ROUTES
+// Run with: cargo test --test route_policies
+
+use atlas_pharma::middleware::route_policy::{RoutePolicy, RoutePolicyReport};
+
+const MAIN_RS: &str = include_str!("../src/main.rs");
+
+#[test]
+fn every_route_has_expected_protection() {
+    let report = RoutePolicyReport::from_source(MAIN_RS);
+
+    assert!(
+        report.is_clean(),
+        "Route policy violations (protect the route or add it to PUBLIC_ROUTES with a reason):\n{:#?}",
+        report.violations
+    );
+}
+
+#[test]
+fn router_walk_sees_the_admin_layers() {
+    let report = RoutePolicyReport::from_source(MAIN_RS);
+    let route = |method: &str, path: &str| {
+        report
+            .routes
+            .iter()
+            .find(|route| route.method == method && route.path == path)
+            .unwrap_or_else(|| panic!("{} {} not found in the router", method, path))
+    };
+
+    let list_users = route("GET", "/api/admin/users");
+    assert!(list_users.has(RoutePolicy::Auth));
+    assert!(list_users.has(RoutePolicy::Admin));
+    assert!(list_users.has(RoutePolicy::RateLimit));
+
+    assert!(route("PUT", "/api/admin/maintenance").has(RoutePolicy::Superadmin));
+    assert!(route("GET", "/api/admin/route-policies").has(RoutePolicy::Admin));
+    assert!(route("POST", "/api/auth/login").has(RoutePolicy::AuthRateLimit));
+
+    let sign = route("POST", "/api/regulatory/documents/:id/sign");
+    assert!(sign.has(RoutePolicy::Auth));
+    assert!(sign.has(RoutePolicy::AuthRateLimit));
+    assert!(route("POST", "/api/regulatory/documents/:id/approve").has(RoutePolicy::AuthRateLimit));
+}